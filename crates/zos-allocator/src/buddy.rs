@@ -0,0 +1,479 @@
+//! Buddy allocator for long-lived WASM processes
+//!
+//! Unlike [`BumpAllocator`](crate::BumpAllocator), this allocator reuses
+//! freed memory, so a service that runs for the lifetime of the system does
+//! not slowly exhaust its heap.
+//!
+//! # Design
+//!
+//! Every allocation is rounded up to a power-of-two block of at least
+//! `MIN_BLOCK` bytes. Free blocks are kept in one list per size (order).
+//! Allocating splits a larger block in halves ("buddies") until it fits;
+//! freeing merges a block with its buddy whenever both are free, so the
+//! heap returns to large contiguous blocks once memory is released.
+//!
+//! Block bookkeeping lives inside the free blocks themselves, plus a bitmap
+//! (one bit per `MIN_BLOCK`) carved from the start of the heap that marks
+//! where free blocks begin. Allocated blocks carry no header: `dealloc`
+//! recomputes the block size from the `Layout`.
+//!
+//! # Trade-offs
+//!
+//! - Rounding to powers of two wastes up to half of each allocation
+//! - The bitmap costs 1/128th of the heap
+//! - Alignments above `ARENA_ALIGN` are not supported (allocation fails)
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{ensure_memory, heap_base};
+
+/// Smallest block handed out (holds a free-list node).
+const MIN_BLOCK: usize = 16;
+
+/// log2(MIN_BLOCK)
+const MIN_ORDER: u32 = MIN_BLOCK.trailing_zeros();
+
+/// One free list per block order up to 2^31 bytes.
+const MAX_ORDERS: usize = 32;
+
+/// The arena starts on this boundary, so a block is aligned to its own
+/// size up to this value.
+const ARENA_ALIGN: usize = 4096;
+
+/// End-of-list marker.
+const NIL: u32 = u32::MAX;
+
+/// Free-list node stored at the start of every free block.
+///
+/// Links are arena offsets rather than pointers so the node fits in
+/// `MIN_BLOCK` bytes on 64-bit hosts too.
+#[repr(C)]
+struct FreeBlock {
+    next: u32,
+    prev: u32,
+    order: u32,
+}
+
+/// Buddy heap over a fixed region of memory.
+pub(crate) struct Heap {
+    /// Address of the first block
+    arena: usize,
+    /// Usable bytes from `arena`
+    arena_len: usize,
+    /// One bit per MIN_BLOCK: set where a free block begins
+    bitmap: *mut u8,
+    /// Head of the free list for each order
+    free: [u32; MAX_ORDERS],
+    /// Bytes in allocated blocks
+    used: usize,
+}
+
+impl Heap {
+    /// Build a heap over `len` bytes at `start`.
+    ///
+    /// Returns `None` if the region is too small to hold any block.
+    ///
+    /// # Safety
+    ///
+    /// The region must be writable memory owned exclusively by this heap.
+    pub(crate) unsafe fn new(start: usize, len: usize) -> Option<Self> {
+        let end = start.checked_add(len)?;
+        let bitmap_len = len / MIN_BLOCK / 8 + 1;
+        let arena = align_up(start.checked_add(bitmap_len)?, ARENA_ALIGN)?;
+        if arena >= end {
+            return None;
+        }
+        let arena_len = ((end - arena) / MIN_BLOCK * MIN_BLOCK).min(u32::MAX as usize);
+        if arena_len == 0 {
+            return None;
+        }
+
+        let bitmap = start as *mut u8;
+        core::ptr::write_bytes(bitmap, 0, arena_len / MIN_BLOCK / 8 + 1);
+        let mut heap = Self {
+            arena,
+            arena_len,
+            bitmap,
+            free: [NIL; MAX_ORDERS],
+            used: 0,
+        };
+
+        // Cover the arena with the largest blocks that fit. Sizes only
+        // shrink, so each offset stays a multiple of its block's size.
+        let mut offset = 0;
+        while arena_len - offset >= MIN_BLOCK {
+            let remaining = arena_len - offset;
+            let order = usize::BITS - 1 - remaining.leading_zeros();
+            heap.push(offset, order);
+            offset += 1 << order;
+        }
+        Some(heap)
+    }
+
+    /// Allocate a block for `layout`, or null if none is free.
+    pub(crate) unsafe fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        let Some(order) = order_for(layout) else {
+            return core::ptr::null_mut();
+        };
+        let Some(mut current) = (order..MAX_ORDERS as u32).find(|&k| self.free[k as usize] != NIL)
+        else {
+            return core::ptr::null_mut();
+        };
+
+        let offset = self.free[current as usize] as usize;
+        self.remove(offset, current);
+        // Split, keeping the lower half and freeing the upper buddy
+        while current > order {
+            current -= 1;
+            self.push(offset + (1 << current), current);
+        }
+        self.used += 1 << order;
+        (self.arena + offset) as *mut u8
+    }
+
+    /// Return a block to the heap, merging it with free buddies.
+    pub(crate) unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        let Some(mut order) = order_for(layout) else {
+            return;
+        };
+        let mut offset = ptr as usize - self.arena;
+        self.used -= 1 << order;
+
+        while (order as usize) < MAX_ORDERS - 1 {
+            let size = 1usize << order;
+            let buddy = offset ^ size;
+            if buddy + size > self.arena_len
+                || !self.is_free(buddy)
+                || (*self.node(buddy)).order != order
+            {
+                break;
+            }
+            self.remove(buddy, order);
+            offset = offset.min(buddy);
+            order += 1;
+        }
+        self.push(offset, order);
+    }
+
+    /// Whether a block of `old` can be resized to `new` where it is.
+    pub(crate) fn fits_in_place(old: Layout, new: Layout) -> bool {
+        order_for(old).is_some() && order_for(old) == order_for(new)
+    }
+
+    /// Bytes in allocated blocks (after rounding).
+    pub(crate) fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Size of the largest free block, i.e. the largest allocation that
+    /// would currently succeed.
+    pub(crate) fn largest_free_block(&self) -> usize {
+        (0..MAX_ORDERS)
+            .rev()
+            .find(|&k| self.free[k] != NIL)
+            .map_or(0, |k| 1 << k)
+    }
+
+    fn node(&self, offset: usize) -> *mut FreeBlock {
+        (self.arena + offset) as *mut FreeBlock
+    }
+
+    unsafe fn push(&mut self, offset: usize, order: u32) {
+        let head = self.free[order as usize];
+        self.node(offset).write(FreeBlock {
+            next: head,
+            prev: NIL,
+            order,
+        });
+        if head != NIL {
+            (*self.node(head as usize)).prev = offset as u32;
+        }
+        self.free[order as usize] = offset as u32;
+        self.set_free(offset, true);
+    }
+
+    unsafe fn remove(&mut self, offset: usize, order: u32) {
+        let FreeBlock { next, prev, .. } = self.node(offset).read();
+        if prev == NIL {
+            self.free[order as usize] = next;
+        } else {
+            (*self.node(prev as usize)).next = next;
+        }
+        if next != NIL {
+            (*self.node(next as usize)).prev = prev;
+        }
+        self.set_free(offset, false);
+    }
+
+    unsafe fn is_free(&self, offset: usize) -> bool {
+        let bit = offset / MIN_BLOCK;
+        *self.bitmap.add(bit / 8) & (1 << (bit % 8)) != 0
+    }
+
+    unsafe fn set_free(&mut self, offset: usize, free: bool) {
+        let bit = offset / MIN_BLOCK;
+        let byte = self.bitmap.add(bit / 8);
+        if free {
+            *byte |= 1 << (bit % 8);
+        } else {
+            *byte &= !(1 << (bit % 8));
+        }
+    }
+}
+
+/// Block order serving `layout`, or `None` if it cannot be served.
+fn order_for(layout: Layout) -> Option<u32> {
+    if layout.align() > ARENA_ALIGN {
+        return None;
+    }
+    let size = layout.size().max(layout.align()).max(MIN_BLOCK);
+    let order = size.checked_next_power_of_two()?.trailing_zeros();
+    if order as usize >= MAX_ORDERS {
+        return None;
+    }
+    debug_assert!(order >= MIN_ORDER);
+    Some(order)
+}
+
+fn align_up(addr: usize, align: usize) -> Option<usize> {
+    Some(addr.checked_add(align - 1)? & !(align - 1))
+}
+
+/// Buddy allocator with configurable heap size.
+///
+/// The heap starts at `__heap_base` like [`BumpAllocator`](crate::BumpAllocator)
+/// and is set up on first use. Opt in with
+/// `zos_allocator::init!(size, BuddyAllocator)`.
+pub struct BuddyAllocator<const SIZE: usize> {
+    locked: AtomicBool,
+    heap: UnsafeCell<Option<Heap>>,
+    initialized: AtomicBool,
+}
+
+impl<const SIZE: usize> BuddyAllocator<SIZE> {
+    /// Create a new buddy allocator.
+    pub const fn new() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            heap: UnsafeCell::new(None),
+            initialized: AtomicBool::new(false),
+        }
+    }
+
+    /// Bytes currently allocated (after rounding to block sizes).
+    pub fn used(&self) -> usize {
+        self.with_heap(|heap| heap.used()).unwrap_or(0)
+    }
+
+    /// Largest allocation that would currently succeed.
+    pub fn largest_free_block(&self) -> usize {
+        self.with_heap(|heap| heap.largest_free_block())
+            .unwrap_or(0)
+    }
+
+    /// Run `f` on the heap under the lock, setting the heap up on first use.
+    fn with_heap<R>(&self, f: impl FnOnce(&mut Heap) -> R) -> Option<R> {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        // SAFETY: the lock gives exclusive access to the heap state, and
+        // the region after __heap_base belongs to the allocator
+        let heap = unsafe { &mut *self.heap.get() };
+        if !self.initialized.swap(true, Ordering::Relaxed) {
+            // Free-list nodes are written across the whole region up front
+            *heap = if ensure_memory(heap_base() + SIZE) {
+                unsafe { Heap::new(heap_base(), SIZE) }
+            } else {
+                None
+            };
+        }
+        let result = heap.as_mut().map(f);
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
+
+impl<const SIZE: usize> Default for BuddyAllocator<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: All access to the heap state goes through the spin lock
+unsafe impl<const SIZE: usize> Sync for BuddyAllocator<SIZE> {}
+
+unsafe impl<const SIZE: usize> GlobalAlloc for BuddyAllocator<SIZE> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.with_heap(|heap| heap.alloc(layout))
+            .unwrap_or(core::ptr::null_mut())
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.with_heap(|heap| heap.dealloc(ptr, layout));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        if Heap::fits_in_place(layout, new_layout) {
+            return ptr;
+        }
+        let new_ptr = self.alloc(new_layout);
+        if !new_ptr.is_null() {
+            core::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+            self.dealloc(ptr, layout);
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    /// A heap over a leaked host buffer of `len` bytes.
+    fn test_heap(len: usize) -> Heap {
+        let buffer: &'static mut [u64] = vec![0u64; len / 8].leak();
+        unsafe { Heap::new(buffer.as_mut_ptr() as usize, len) }.expect("heap should fit")
+    }
+
+    fn layout(size: usize) -> Layout {
+        Layout::from_size_align(size, 8).unwrap()
+    }
+
+    #[test]
+    fn test_freed_block_is_reused() {
+        let mut heap = test_heap(64 * 1024);
+        let first = unsafe { heap.alloc(layout(100)) };
+        assert!(!first.is_null());
+        assert_eq!(heap.used(), 128);
+
+        unsafe { heap.dealloc(first, layout(100)) };
+        assert_eq!(heap.used(), 0);
+        let second = unsafe { heap.alloc(layout(100)) };
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_freeing_everything_coalesces() {
+        let mut heap = test_heap(64 * 1024);
+        let initial = heap.largest_free_block();
+
+        let mut blocks = Vec::new();
+        loop {
+            let ptr = unsafe { heap.alloc(layout(24)) };
+            if ptr.is_null() {
+                break;
+            }
+            blocks.push(ptr);
+        }
+        assert!(blocks.len() > 1000);
+        assert!(heap.largest_free_block() < 32);
+
+        for ptr in blocks {
+            unsafe { heap.dealloc(ptr, layout(24)) };
+        }
+        assert_eq!(heap.used(), 0);
+        assert_eq!(heap.largest_free_block(), initial);
+        assert!(!unsafe { heap.alloc(layout(initial)) }.is_null());
+    }
+
+    #[test]
+    fn test_interleaved_frees_fragment_until_buddies_return() {
+        let mut heap = test_heap(64 * 1024);
+        let blocks: Vec<*mut u8> = (0..256)
+            .map(|_| unsafe { heap.alloc(layout(64)) })
+            .collect();
+        assert!(blocks.iter().all(|p| !p.is_null()));
+        let before = heap.largest_free_block();
+
+        // Every other block freed: no two free blocks are buddies
+        for ptr in blocks.iter().step_by(2) {
+            unsafe { heap.dealloc(*ptr, layout(64)) };
+        }
+        assert_eq!(heap.largest_free_block(), before);
+
+        // Freeing the rest merges them all back
+        for ptr in blocks.iter().skip(1).step_by(2) {
+            unsafe { heap.dealloc(*ptr, layout(64)) };
+        }
+        assert!(heap.largest_free_block() >= 256 * 64);
+    }
+
+    #[test]
+    fn test_mixed_sizes_survive_churn() {
+        let mut heap = test_heap(256 * 1024);
+        let initial = heap.largest_free_block();
+
+        // Many rounds of allocate-then-free-half, as a long-running
+        // service would; a bump allocator runs out long before this ends
+        let mut live: Vec<(*mut u8, usize)> = Vec::new();
+        for round in 0..2000usize {
+            let size = 16 + (round * 37) % 900;
+            let ptr = unsafe { heap.alloc(layout(size)) };
+            assert!(!ptr.is_null(), "allocation failed in round {}", round);
+            unsafe { core::ptr::write_bytes(ptr, round as u8, size) };
+            live.push((ptr, size));
+            if live.len() > 32 {
+                let (ptr, size) = live.remove(round % live.len());
+                unsafe { heap.dealloc(ptr, layout(size)) };
+            }
+        }
+        for (ptr, size) in live {
+            unsafe { heap.dealloc(ptr, layout(size)) };
+        }
+        assert_eq!(heap.used(), 0);
+        assert_eq!(heap.largest_free_block(), initial);
+    }
+
+    #[test]
+    fn test_alignment() {
+        let mut heap = test_heap(64 * 1024);
+        for align in [1, 2, 8, 64, 512, 4096] {
+            let layout = Layout::from_size_align(24, align).unwrap();
+            let ptr = unsafe { heap.alloc(layout) };
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize % align, 0);
+        }
+        let too_aligned = Layout::from_size_align(24, 8192).unwrap();
+        assert!(unsafe { heap.alloc(too_aligned) }.is_null());
+    }
+
+    #[test]
+    fn test_oom_returns_null() {
+        let mut heap = test_heap(16 * 1024);
+        assert!(unsafe { heap.alloc(layout(64 * 1024)) }.is_null());
+        assert_eq!(heap.used(), 0);
+        assert!(!unsafe { heap.alloc(layout(1024)) }.is_null());
+    }
+
+    #[test]
+    fn test_non_power_of_two_heap() {
+        // Not a power of two: the arena is seeded with several top blocks
+        let mut heap = test_heap(7 * 4096 + 4096);
+        let mut total = 0;
+        loop {
+            let ptr = unsafe { heap.alloc(layout(4096)) };
+            if ptr.is_null() {
+                break;
+            }
+            total += 4096;
+        }
+        assert!(total >= 6 * 4096);
+    }
+
+    #[test]
+    fn test_realloc_within_block_stays_in_place() {
+        assert!(Heap::fits_in_place(layout(40), layout(60)));
+        assert!(!Heap::fits_in_place(layout(40), layout(70)));
+    }
+}
@@ -1,20 +1,29 @@
-//! Bump Allocator for Zero OS WASM Processes
+//! Allocators for Zero OS WASM Processes
 //!
-//! Provides a simple bump allocator with configurable heap size via const generic.
+//! Provides global allocators with configurable heap size via const generic.
 //! This eliminates code duplication across all WASM binaries that need an allocator.
 //!
+//! - [`BumpAllocator`] (default): never frees, for short-lived processes
+//! - [`BuddyAllocator`]: reuses freed memory, for long-lived services
+//! - [`MessagePool`]: recycles IPC message buffers under either allocator
+//!
 //! # Usage
 //!
 //! ```ignore
 //! // At the crate root level:
-//! zos_allocator::init!(1024 * 1024); // 1MB heap
+//! zos_allocator::init!(1024 * 1024); // 1MB bump heap
+//!
+//! // Or, for a process that runs indefinitely:
+//! zos_allocator::init!(4 * 1024 * 1024, BuddyAllocator);
 //! ```
 //!
 //! # Heap Sizes by Binary
 //!
 //! | Binary | Heap Size | Rationale |
 //! |--------|-----------|-----------|
-//! | init | 4MB | Service registry, loading large binaries |
+//! | init | 7MB | Service registry, loading large binaries (bump) |
+//! | vfs | 8MB | Content buffers and caches (buddy) |
+//! | identity | 4MB | Key material and remote requests (buddy) |
 //! | idle | 64KB | Minimal - does nothing |
 //! | pingpong | 1MB | Latency measurement with vectors |
 //! | sender | 1MB | Message burst handling |
@@ -30,9 +39,17 @@
 
 #![no_std]
 
+extern crate alloc;
+
 use core::alloc::{GlobalAlloc, Layout};
 use core::sync::atomic::{AtomicUsize, Ordering};
 
+mod buddy;
+pub mod pool;
+
+pub use buddy::BuddyAllocator;
+pub use pool::{MessagePool, PoolStats};
+
 // Import the __heap_base symbol from wasm-ld
 // This tells us where the data section ends and the heap can begin
 #[cfg(target_arch = "wasm32")]
@@ -54,22 +71,47 @@ fn heap_base() -> usize {
     0x10000 // Fallback for non-WASM (not actually used)
 }
 
+/// Grow linear memory so that every address below `end` is backed.
+///
+/// Processes start with the `--initial-memory` set in `.cargo/config.toml`
+/// and may grow up to its `--max-memory`. Returns false past that cap.
+#[cfg(target_arch = "wasm32")]
+fn ensure_memory(end: usize) -> bool {
+    const PAGE_SIZE: usize = 64 * 1024;
+    let have = core::arch::wasm32::memory_size(0) * PAGE_SIZE;
+    if end <= have {
+        return true;
+    }
+    let pages = (end - have).div_ceil(PAGE_SIZE);
+    core::arch::wasm32::memory_grow(0, pages) != usize::MAX
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn ensure_memory(_end: usize) -> bool {
+    true // Host tests allocate from their own buffers
+}
+
 /// Initialize the global allocator with the specified heap size in bytes.
 ///
 /// This macro must be called exactly once at the crate root level.
-/// It only activates on wasm32 targets.
+/// It only activates on wasm32 targets. The allocator defaults to
+/// [`BumpAllocator`]; name another one as the second argument.
 ///
 /// # Example
 ///
 /// ```ignore
 /// zos_allocator::init!(1024 * 1024); // 1MB heap
+/// zos_allocator::init!(1024 * 1024, BuddyAllocator); // 1MB heap with free
 /// ```
 #[macro_export]
 macro_rules! init {
     ($heap_size:expr) => {
+        $crate::init!($heap_size, BumpAllocator);
+    };
+    ($heap_size:expr, $allocator:ident) => {
         #[cfg(target_arch = "wasm32")]
         #[global_allocator]
-        static ALLOCATOR: $crate::BumpAllocator<{ $heap_size }> = $crate::BumpAllocator::new();
+        static ALLOCATOR: $crate::$allocator<{ $heap_size }> = $crate::$allocator::new();
     };
 }
 
@@ -79,7 +121,7 @@ macro_rules! init {
 /// avoid conflicts with the WASM data section and stack.
 ///
 /// This is a simple "bump pointer" allocator that:
-/// - Allocates by incrementing a pointer
+/// - Allocates by incrementing a pointer, growing linear memory as needed
 /// - Never deallocates (suitable for short-lived WASM processes)
 /// - Is thread-safe via atomic operations
 pub struct BumpAllocator<const SIZE: usize> {
@@ -95,6 +137,12 @@ impl<const SIZE: usize> BumpAllocator<SIZE> {
     }
 }
 
+impl<const SIZE: usize> Default for BumpAllocator<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // SAFETY: The allocator uses atomic operations for thread safety
 unsafe impl<const SIZE: usize> Sync for BumpAllocator<SIZE> {}
 
@@ -113,6 +161,11 @@ unsafe impl<const SIZE: usize> GlobalAlloc for BumpAllocator<SIZE> {
                 return core::ptr::null_mut();
             }
 
+            // Memory starts small; grow it as the heap fills
+            if !ensure_memory(heap_start + new_head) {
+                return core::ptr::null_mut();
+            }
+
             if self
                 .head
                 .compare_exchange_weak(head, new_head, Ordering::SeqCst, Ordering::Relaxed)
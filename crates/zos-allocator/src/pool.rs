@@ -0,0 +1,166 @@
+//! Message buffer pool
+//!
+//! Every IPC message carries its payload in a `Vec<u8>`. Under the bump
+//! allocator a dropped payload is never reclaimed, so a busy process grows
+//! its heap by the size of every message it receives. A [`MessagePool`]
+//! keeps finished buffers and hands their capacity out again for later
+//! messages.
+
+use alloc::vec::Vec;
+
+/// Most buffers a pool keeps.
+pub const MAX_POOLED_BUFFERS: usize = 32;
+
+/// Largest buffer a pool keeps. Bigger ones are rare (binaries, bulk reads)
+/// and would pin a lot of memory.
+pub const MAX_POOLED_CAPACITY: usize = 16 * 1024;
+
+/// Pool counters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Buffers served from the pool
+    pub hits: u64,
+    /// Buffers that had to be allocated
+    pub misses: u64,
+    /// Buffers returned to the pool
+    pub recycled: u64,
+    /// Returned buffers dropped because the pool was full or they were too big
+    pub discarded: u64,
+}
+
+impl PoolStats {
+    /// Fraction of buffers served from the pool (0.0 before any request).
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// A free list of message buffers.
+#[derive(Debug, Default)]
+pub struct MessagePool {
+    free: Vec<Vec<u8>>,
+    stats: PoolStats,
+}
+
+impl MessagePool {
+    /// Create an empty pool.
+    pub const fn new() -> Self {
+        Self {
+            free: Vec::new(),
+            stats: PoolStats {
+                hits: 0,
+                misses: 0,
+                recycled: 0,
+                discarded: 0,
+            },
+        }
+    }
+
+    /// An empty buffer with room for at least `len` bytes.
+    pub fn take(&mut self, len: usize) -> Vec<u8> {
+        match self.free.iter().rposition(|buf| buf.capacity() >= len) {
+            Some(index) => {
+                self.stats.hits += 1;
+                self.free.swap_remove(index)
+            }
+            None => {
+                self.stats.misses += 1;
+                Vec::with_capacity(len)
+            }
+        }
+    }
+
+    /// A buffer holding a copy of `data`.
+    pub fn copy_of(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut buf = self.take(data.len());
+        buf.extend_from_slice(data);
+        buf
+    }
+
+    /// Return a buffer for reuse.
+    pub fn give(&mut self, mut buf: Vec<u8>) {
+        if buf.capacity() == 0 {
+            return;
+        }
+        if buf.capacity() > MAX_POOLED_CAPACITY || self.free.len() >= MAX_POOLED_BUFFERS {
+            self.stats.discarded += 1;
+            return;
+        }
+        buf.clear();
+        self.free.push(buf);
+        self.stats.recycled += 1;
+    }
+
+    /// Buffers currently pooled.
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Whether no buffers are pooled.
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// Counters since the pool was created.
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_recycled_capacity_is_reused() {
+        let mut pool = MessagePool::new();
+        let buf = pool.copy_of(&[1, 2, 3, 4]);
+        assert_eq!(buf, [1, 2, 3, 4]);
+        let ptr = buf.as_ptr();
+        pool.give(buf);
+
+        let again = pool.copy_of(&[9, 9]);
+        assert_eq!(again, [9, 9]);
+        assert_eq!(again.as_ptr(), ptr);
+        assert_eq!(
+            pool.stats(),
+            PoolStats {
+                hits: 1,
+                misses: 1,
+                recycled: 1,
+                discarded: 0,
+            }
+        );
+        assert_eq!(pool.stats().hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn test_take_skips_buffers_that_are_too_small() {
+        let mut pool = MessagePool::new();
+        pool.give(Vec::with_capacity(8));
+        let buf = pool.take(64);
+        assert!(buf.capacity() >= 64);
+        assert_eq!(pool.stats().misses, 1);
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn test_pool_is_bounded() {
+        let mut pool = MessagePool::new();
+        pool.give(vec![0; MAX_POOLED_CAPACITY + 1]);
+        pool.give(Vec::new());
+        assert!(pool.is_empty());
+
+        for _ in 0..MAX_POOLED_BUFFERS + 3 {
+            pool.give(Vec::with_capacity(16));
+        }
+        assert_eq!(pool.len(), MAX_POOLED_BUFFERS);
+        assert_eq!(pool.stats().discarded, 4);
+    }
+}
@@ -4,6 +4,7 @@
 //! - Bidirectional IPC
 //! - State management
 //! - User input handling
+//! - Palette commands declared in the manifest
 
 mod state;
 
@@ -13,7 +14,7 @@ use alloc::format;
 use alloc::string::{String, ToString};
 use crate::protocol::{tags, InputEvent};
 use crate::framework::{
-    AppContext, AppError, AppManifest, ControlFlow, Message, ZeroApp,
+    AppContext, AppError, AppManifest, Command, ControlFlow, Message, ZeroApp,
     CALCULATOR_MANIFEST,
 };
 use crate::syscall;
@@ -227,6 +228,12 @@ impl ZeroApp for CalculatorApp {
         Ok(())
     }
 
+    fn on_command(&mut self, ctx: &AppContext, command: Command) -> Result<(), AppError> {
+        // Manifest command IDs are button names
+        self.handle_button(&command.id);
+        self.send_state(ctx)
+    }
+
     fn shutdown(&mut self, _ctx: &AppContext) {
         syscall::debug("Calculator: shutting down");
     }
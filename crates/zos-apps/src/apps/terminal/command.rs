@@ -24,9 +24,12 @@ pub enum Command {
     /// Request spawning a new process
     Spawn { process_type: String },
 
-    /// Request killing a process (not yet implemented)
+    /// Kill a process
     Kill { pid: u32 },
 
+    /// Kill a process group; without a pgid, what this terminal spawned
+    KillGroup { pgid: Option<u32> },
+
     /// Grant a capability to another process
     Grant {
         from_slot: u32,
@@ -100,6 +103,17 @@ impl Command {
                 }
             }
 
+            "kill" if args.first() == Some(&"-g") => match args.get(1) {
+                None => Ok(Command::KillGroup { pgid: None }),
+                Some(pgid) => pgid
+                    .parse::<u32>()
+                    .map(|pgid| Command::KillGroup { pgid: Some(pgid) })
+                    .map_err(|_| ParseError::InvalidArgument {
+                        argument: "pgid",
+                        reason: "must be a number",
+                    }),
+            },
+
             "kill" => {
                 if args.is_empty() {
                     Err(ParseError::MissingArgument {
@@ -187,7 +201,8 @@ impl Command {
             Command::Ps => "ps - List running processes",
             Command::Caps => "caps - List capabilities",
             Command::Spawn { .. } => "spawn <process_type> - Request process spawn",
-            Command::Kill { .. } => "kill <pid> - Request process termination",
+            Command::Kill { .. } => "kill <pid> - Kill a process",
+            Command::KillGroup { .. } => "kill -g [pgid] - Kill a process group",
             Command::Grant { .. } => "grant <slot> <pid> <perms> - Grant capability (perms: r/w/g)",
             Command::Revoke { .. } => "revoke <slot> - Revoke capability",
             Command::Echo { .. } => "echo <text> - Echo text",
@@ -256,6 +271,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_kill_group() {
+        assert_eq!(
+            Command::parse("kill -g"),
+            Ok(Command::KillGroup { pgid: None })
+        );
+        assert_eq!(
+            Command::parse("kill -g 7"),
+            Ok(Command::KillGroup { pgid: Some(7) })
+        );
+        assert_eq!(
+            Command::parse("kill -g abc"),
+            Err(ParseError::InvalidArgument {
+                argument: "pgid",
+                reason: "must be a number"
+            })
+        );
+    }
+
     #[test]
     fn test_parse_grant() {
         let result = Command::parse("grant 1 42 rw");
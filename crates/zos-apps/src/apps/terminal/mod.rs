@@ -30,7 +30,7 @@ use crate::framework::{
     TERMINAL_MANIFEST,
 };
 use crate::syscall;
-use zos_process::{error, ObjectType, MSG_CAP_REVOKED};
+use zos_process::{error, syscall_error, ObjectType, MSG_CAP_REVOKED};
 
 /// Terminal application state
 #[derive(Default)]
@@ -232,6 +232,7 @@ impl TerminalApp {
             Command::Caps => self.cmd_caps(),
             Command::Spawn { process_type } => self.cmd_spawn(&process_type),
             Command::Kill { pid } => self.cmd_kill(pid),
+            Command::KillGroup { pgid } => self.cmd_kill_group(pgid),
             Command::Grant { from_slot, to_pid, permissions } => {
                 self.cmd_grant(from_slot, to_pid, permissions)
             }
@@ -254,7 +255,8 @@ impl TerminalApp {
         self.println("Process Management:");
         self.println("  ps                - List running processes");
         self.println("  spawn <type>      - Request process spawn");
-        self.println("  kill <pid>        - Request process termination");
+        self.println("  kill <pid>        - Kill a process");
+        self.println("  kill -g [pgid]    - Kill a process group (default: what I spawned)");
         self.println("");
         self.println("Capabilities:");
         self.println("  caps              - List my capabilities");
@@ -329,9 +331,30 @@ impl TerminalApp {
         self.println(&format!("Requested spawn of '{}'...", process_type));
     }
 
-    fn cmd_kill(&mut self, _pid: u32) {
-        self.println("Error: Kill requires supervisor privilege");
-        self.println("(Terminal cannot directly kill processes yet)");
+    fn cmd_kill(&mut self, pid: u32) {
+        match syscall::kill(pid) {
+            Ok(()) => self.println(&format!("Killed process {}", pid)),
+            Err(_) => self.println(&format!(
+                "Error: cannot kill PID {} (no such process, or no Process capability for it)",
+                pid
+            )),
+        }
+    }
+
+    /// Kill a process group. Processes spawned from this terminal join its
+    /// group, which it leads, so by default this kills those.
+    fn cmd_kill_group(&mut self, pgid: Option<u32>) {
+        let pgid = pgid.unwrap_or_else(syscall::get_pid);
+        match syscall::kill_group(pgid) {
+            Ok(count) => self.println(&format!("Killed {} process(es) in group {}", count, pgid)),
+            Err(e) if e as i32 == syscall_error::NOT_FOUND => {
+                self.println(&format!("Error: no process group {}", pgid))
+            }
+            Err(e) if e as i32 == syscall_error::PERMISSION_DENIED => {
+                self.println(&format!("Error: may kill no process in group {}", pgid))
+            }
+            Err(e) => self.println(&format!("Error: {}", self.format_cap_error(e))),
+        }
     }
 
     fn cmd_grant(&mut self, from_slot: u32, to_pid: u32, perms: syscall::Permissions) {
@@ -388,6 +411,12 @@ impl ZeroApp for TerminalApp {
     fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
         syscall::debug(&format!("Terminal starting (PID {})", ctx.pid));
 
+        // Lead a process group of our own; what we spawn joins it, so
+        // `kill -g` can kill it all
+        if let Err(e) = syscall::setpgid(ctx.pid, 0) {
+            syscall::debug(&format!("Terminal: setpgid failed: {}", e));
+        }
+
         self.println("Zero OS Terminal");
         self.println("Type 'help' for available commands.");
         self.println("");
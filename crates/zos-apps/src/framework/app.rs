@@ -2,6 +2,7 @@
 //!
 //! Defines the interface that all Zero applications implement.
 
+use super::command::Command;
 use super::error::AppError;
 use super::manifest::AppManifest;
 use alloc::string::String;
@@ -187,6 +188,9 @@ pub struct Message {
     /// capabilities that were transferred with this message.
     pub cap_slots: Vec<u32>,
 
+    /// Correlation ID the sender set, to be echoed in the response
+    pub correlation_id: Option<u32>,
+
     /// Message payload data
     pub data: Vec<u8>,
 }
@@ -198,9 +202,23 @@ impl Message {
             tag,
             from_pid,
             cap_slots,
+            correlation_id: None,
             data,
         }
     }
+
+    /// Set the correlation ID the message arrived with.
+    pub fn with_correlation_id(mut self, correlation_id: Option<u32>) -> Self {
+        self.correlation_id = correlation_id;
+        self
+    }
+}
+
+impl Drop for Message {
+    fn drop(&mut self) {
+        // Hand the payload buffer back for the next receive
+        zos_process::pool::recycle(core::mem::take(&mut self.data));
+    }
 }
 
 /// The Program Interface that all Zero apps implement.
@@ -210,6 +228,7 @@ impl Message {
 /// 1. **init()**: Called once when the app starts. Initialize state, set up IPC endpoints.
 /// 2. **update()**: Called repeatedly in the event loop. Perform periodic work, update state.
 /// 3. **on_message()**: Called when a message is received via IPC.
+///    Palette commands go to **on_command()** instead.
 /// 4. **shutdown()**: Called before the app exits. Clean up resources.
 ///
 /// # Invariants
@@ -302,6 +321,18 @@ pub trait ZeroApp {
     /// recoverable issues (invalid message format, etc.).
     fn on_message(&mut self, ctx: &AppContext, msg: Message) -> Result<(), AppError>;
 
+    /// Called when one of the app's commands is invoked from the palette.
+    ///
+    /// Commands declared in the manifest (and any the app registers itself)
+    /// arrive here instead of `on_message()`. The default ignores them.
+    ///
+    /// # Errors
+    ///
+    /// Errors are logged like those of `on_message()`.
+    fn on_command(&mut self, _ctx: &AppContext, _command: Command) -> Result<(), AppError> {
+        Ok(())
+    }
+
     /// Called before the app exits.
     ///
     /// Clean up resources, save state, close IPC connections.
@@ -0,0 +1,79 @@
+//! Palette Commands
+//!
+//! Apps offer commands to the system-wide command palette by listing them
+//! in their manifest (or by sending `MSG_COMMAND_REGISTER` to the command
+//! service themselves). The runtime announces manifest commands at startup
+//! and hands each invocation the palette makes to `ZeroApp::on_command`.
+
+use super::app::Message;
+use super::manifest::AppManifest;
+use alloc::format;
+use alloc::string::String;
+use serde::Deserialize;
+use serde_json::Value;
+use zos_process as syscall;
+
+/// Invocations are delivered by Init on behalf of the command service
+const INIT_PID: u32 = 1;
+
+/// A command invoked from the palette.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Command {
+    /// ID the command was declared with
+    pub id: String,
+    /// Arguments the palette passed (`null` if none)
+    #[serde(default)]
+    pub args: Value,
+}
+
+impl Command {
+    /// The command carried by `msg`, if it is a `MSG_COMMAND` from Init.
+    pub fn from_message(msg: &Message) -> Option<Self> {
+        if msg.tag != zos_ipc::commands::MSG_COMMAND || msg.from_pid != INIT_PID {
+            return None;
+        }
+        serde_json::from_slice(&msg.data).ok()
+    }
+}
+
+/// Announce the commands declared in `manifest` to the command service.
+///
+/// Goes through the supervisor, which knows the sending PID, so no
+/// capability to the service is needed. Does nothing for a manifest
+/// without commands.
+pub(crate) fn announce(manifest: &AppManifest) {
+    if manifest.commands.is_empty() {
+        return;
+    }
+    let commands: alloc::vec::Vec<Value> = manifest
+        .commands
+        .iter()
+        .map(|c| serde_json::json!({ "id": c.id, "title": c.title, "keywords": c.keywords }))
+        .collect();
+    let request = serde_json::json!({ "app": manifest.id, "commands": commands });
+    let json = serde_json::to_vec(&request).unwrap_or_default();
+    let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+    syscall::debug(&format!("{}{}", zos_ipc::debug::COMMAND_REGISTER, hex));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_from_message() {
+        let tag = zos_ipc::commands::MSG_COMMAND;
+        let msg = Message::new(tag, INIT_PID, vec![], br#"{"id":"clear"}"#.to_vec());
+        let command = Command::from_message(&msg).unwrap();
+        assert_eq!(command.id, "clear");
+        assert!(command.args.is_null());
+
+        // Only Init delivers commands
+        let msg = Message::new(tag, 20, vec![], br#"{"id":"clear"}"#.to_vec());
+        assert_eq!(Command::from_message(&msg), None);
+
+        let msg = Message::new(tag + 1, INIT_PID, vec![], br#"{"id":"clear"}"#.to_vec());
+        assert_eq!(Command::from_message(&msg), None);
+    }
+}
@@ -0,0 +1,188 @@
+//! Graceful Drain
+//!
+//! Before init restarts a service (for an update, or because the supervisor
+//! asked for it) it sends `MSG_SERVICE_DRAIN`. A draining service:
+//!
+//! 1. Refuses new client requests with a retryable error.
+//! 2. Lets its pending operations finish.
+//! 3. Writes a final checkpoint of its [`StatefulService`] state.
+//! 4. Acks with `MSG_SERVICE_DRAINED`, after which init kills and respawns it.
+//!
+//! [`Drain`] tracks where a service is in that sequence. Like
+//! `StatefulService` it does no storage I/O: [`Drain::poll`] hands back the
+//! checkpoint bytes and the service writes them with its own tracked
+//! operation.
+
+use alloc::format;
+use alloc::vec::Vec;
+use zos_process as syscall;
+
+use super::error::AppError;
+use super::stateful::{ServiceState, StatefulService};
+
+/// Where a service is in the drain sequence.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DrainPhase {
+    /// Accepting requests as normal
+    #[default]
+    Serving,
+    /// Refusing new requests, waiting for pending operations
+    Draining,
+    /// Final checkpoint written, waiting for the write to complete
+    Checkpointing,
+    /// Acked to init; waiting to be restarted
+    Drained,
+}
+
+/// What the service should do next while draining.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DrainStep {
+    /// Nothing yet
+    Wait,
+    /// Write these bytes to the checkpoint location and report the outcome
+    /// with `StatefulService::finish_checkpoint`
+    Checkpoint(Vec<u8>),
+    /// Drain complete; send the ack with [`Drain::ack`]
+    Ack,
+}
+
+/// Drain state of a service.
+#[derive(Debug, Default)]
+pub struct Drain {
+    phase: DrainPhase,
+}
+
+impl Drain {
+    /// Current phase.
+    pub fn phase(&self) -> DrainPhase {
+        self.phase
+    }
+
+    /// Whether new requests should be refused.
+    pub fn is_draining(&self) -> bool {
+        self.phase != DrainPhase::Serving
+    }
+
+    /// Handle `MSG_SERVICE_DRAIN`. Returns `false` if already draining.
+    pub fn start(&mut self) -> bool {
+        if self.is_draining() {
+            return false;
+        }
+        self.phase = DrainPhase::Draining;
+        true
+    }
+
+    /// Advance the drain. Call from `update()` while draining.
+    ///
+    /// `idle` is whether the service has no pending operations other than
+    /// its own checkpoint write. A failed final checkpoint is retried.
+    pub fn poll<S: ServiceState>(
+        &mut self,
+        idle: bool,
+        state: &mut StatefulService<S>,
+        now_ms: u64,
+    ) -> Result<DrainStep, AppError> {
+        match self.phase {
+            DrainPhase::Serving | DrainPhase::Drained => Ok(DrainStep::Wait),
+            DrainPhase::Draining => {
+                // A restore still in flight counts as pending work, and a
+                // periodic checkpoint must land before the final one
+                if !idle || !state.is_restored() || state.is_checkpoint_in_flight() {
+                    return Ok(DrainStep::Wait);
+                }
+                let data = state.begin_checkpoint(now_ms)?;
+                self.phase = DrainPhase::Checkpointing;
+                Ok(DrainStep::Checkpoint(data))
+            }
+            DrainPhase::Checkpointing => {
+                if state.is_checkpoint_in_flight() {
+                    Ok(DrainStep::Wait)
+                } else if state.is_dirty() {
+                    // The write failed (or the state changed); try again
+                    self.phase = DrainPhase::Draining;
+                    Ok(DrainStep::Wait)
+                } else {
+                    self.phase = DrainPhase::Drained;
+                    Ok(DrainStep::Ack)
+                }
+            }
+        }
+    }
+
+    /// Tell init the service has drained.
+    pub fn ack(&self) -> Result<(), AppError> {
+        syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::init::MSG_SERVICE_DRAINED,
+            &[],
+        )
+        .map_err(|e| AppError::IpcError(format!("drain ack failed: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::stateful::StateStore;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    struct Pending {
+        requests: Vec<u32>,
+    }
+
+    impl ServiceState for Pending {
+        const SCHEMA_VERSION: u32 = 1;
+    }
+
+    #[test]
+    fn test_drain_sequence() {
+        let mut state = StatefulService::<Pending>::new("test", StateStore::Vfs);
+        state.restore(None);
+        let mut drain = Drain::default();
+        assert_eq!(drain.poll(true, &mut state, 0).unwrap(), DrainStep::Wait);
+
+        assert!(drain.start());
+        assert!(!drain.start());
+        assert!(drain.is_draining());
+
+        // Pending operations hold the checkpoint back
+        assert_eq!(drain.poll(false, &mut state, 0).unwrap(), DrainStep::Wait);
+        assert!(matches!(
+            drain.poll(true, &mut state, 0).unwrap(),
+            DrainStep::Checkpoint(_)
+        ));
+        assert_eq!(drain.phase(), DrainPhase::Checkpointing);
+        assert_eq!(drain.poll(true, &mut state, 0).unwrap(), DrainStep::Wait);
+
+        // A failed write is retried before acking
+        state.finish_checkpoint(false);
+        assert_eq!(drain.poll(true, &mut state, 0).unwrap(), DrainStep::Wait);
+        assert!(matches!(
+            drain.poll(true, &mut state, 0).unwrap(),
+            DrainStep::Checkpoint(_)
+        ));
+        state.finish_checkpoint(true);
+        assert_eq!(drain.poll(true, &mut state, 0).unwrap(), DrainStep::Ack);
+        assert_eq!(drain.phase(), DrainPhase::Drained);
+        assert_eq!(drain.poll(true, &mut state, 0).unwrap(), DrainStep::Wait);
+    }
+
+    #[test]
+    fn test_drain_waits_for_restore_and_periodic_checkpoint() {
+        let mut state = StatefulService::<Pending>::new("test", StateStore::Vfs);
+        let mut drain = Drain::default();
+        drain.start();
+        assert_eq!(drain.poll(true, &mut state, 0).unwrap(), DrainStep::Wait);
+
+        state.restore(None);
+        state.state_mut().requests.push(7);
+        state.begin_checkpoint(0).unwrap();
+        assert_eq!(drain.poll(true, &mut state, 0).unwrap(), DrainStep::Wait);
+        state.finish_checkpoint(true);
+        assert!(matches!(
+            drain.poll(true, &mut state, 0).unwrap(),
+            DrainStep::Checkpoint(_)
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 //! Application Manifest
 //!
-//! Declares application identity and capability requirements.
+//! Declares application identity, capability requirements and the commands
+//! the app offers to the command palette.
 
 // Re-export ObjectType from zos-ipc - the single source of truth for capability types.
 // This ensures all crates use consistent values when granting/checking capabilities.
@@ -68,6 +69,19 @@ pub struct CapabilityRequest {
     pub required: bool,
 }
 
+/// A command the app offers to the command palette
+#[derive(Clone, Debug)]
+pub struct CommandDecl {
+    /// Identifier passed back to the app when invoked
+    /// Example: "clear"
+    pub id: &'static str,
+    /// What the palette shows
+    /// Example: "Clear Calculator"
+    pub title: &'static str,
+    /// Extra words the command is found by
+    pub keywords: &'static [&'static str],
+}
+
 /// Application manifest declaring identity and capabilities
 #[derive(Clone, Debug)]
 pub struct AppManifest {
@@ -88,6 +102,14 @@ pub struct AppManifest {
 
     /// Requested capabilities
     pub capabilities: &'static [CapabilityRequest],
+
+    /// Commands offered to the command palette
+    pub commands: &'static [CommandDecl],
+
+    /// Most memory the app may grow to (bytes); `None` keeps the kernel
+    /// default. Declared by the runtime before `init()`; an app that grows
+    /// past it is terminated.
+    pub memory_limit: Option<usize>,
 }
 
 impl AppManifest {
@@ -104,6 +126,8 @@ impl AppManifest {
             version,
             description,
             capabilities: &[],
+            commands: &[],
+            memory_limit: None,
         }
     }
 
@@ -139,6 +163,8 @@ pub static CLOCK_MANIFEST: AppManifest = AppManifest {
         reason: "Send time updates to display",
         required: true,
     }],
+    commands: &[],
+    memory_limit: Some(16 * 1024 * 1024),
 };
 
 /// Calculator app manifest
@@ -153,6 +179,19 @@ pub static CALCULATOR_MANIFEST: AppManifest = AppManifest {
         reason: "Receive input and send results to display",
         required: true,
     }],
+    commands: &[
+        CommandDecl {
+            id: "clear",
+            title: "Clear Calculator",
+            keywords: &["reset", "ac"],
+        },
+        CommandDecl {
+            id: "negate",
+            title: "Negate Number",
+            keywords: &["sign", "minus"],
+        },
+    ],
+    memory_limit: Some(16 * 1024 * 1024),
 };
 
 /// Terminal app manifest
@@ -175,6 +214,8 @@ pub static TERMINAL_MANIFEST: AppManifest = AppManifest {
             required: false,
         },
     ],
+    commands: &[],
+    memory_limit: None,
 };
 
 /// Settings app manifest
@@ -197,4 +238,6 @@ pub static SETTINGS_MANIFEST: AppManifest = AppManifest {
             required: false,
         },
     ],
+    commands: &[],
+    memory_limit: Some(32 * 1024 * 1024),
 };
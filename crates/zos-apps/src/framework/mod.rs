@@ -5,21 +5,38 @@
 //! - **ZeroApp**: The trait all apps implement
 //! - **AppContext**: Execution context provided to app methods
 //! - **AppRuntime**: Event loop that drives apps
-//! - **AppManifest**: Declarative capability requirements
+//! - **AppManifest**: Declarative capability requirements and palette commands
+//! - **Command**: A palette command invoked on the app
+//! - **StatefulService**: Checkpointed service state that survives restarts
+//! - **Drain**: Graceful drain before init restarts a service
+//! - **Reactor**: Sequential async code over storage, keystore and network syscalls
+//! - **Watchdog**: Timer-driven detection of requests that never complete
 
 mod app;
+mod command;
+mod drain;
 mod error;
 mod manifest;
+mod reactor;
 mod runtime;
+mod stateful;
+mod watchdog;
 
 pub use app::{AppContext, ControlFlow, Message, SessionId, UserContext, UserId, ZeroApp};
+pub use command::Command;
+pub use drain::{Drain, DrainPhase, DrainStep};
 pub use error::{AppError, ProtocolError};
 pub use manifest::{
-    AppManifest, CapabilityRequest, ObjectType, Permissions,
+    AppManifest, CapabilityRequest, CommandDecl, ObjectType, Permissions,
     // Factory manifests
     CALCULATOR_MANIFEST, CLOCK_MANIFEST, SETTINGS_MANIFEST, TERMINAL_MANIFEST,
 };
+pub use reactor::{Call, Completion, Io, Reactor, Source};
 pub use runtime::AppRuntime;
+pub use stateful::{
+    Restored, ServiceState, StateStore, StatefulService, DEFAULT_CHECKPOINT_INTERVAL_MS,
+};
+pub use watchdog::{Watchdog, DEFAULT_WATCHDOG_PERIOD_MS};
 
 use zos_process as syscall;
 
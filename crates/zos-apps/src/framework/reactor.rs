@@ -0,0 +1,579 @@
+//! Reactor - sequential async code over request/response syscalls
+//!
+//! Storage, keystore and network syscalls return a request ID right away
+//! and deliver their result later as a message (MSG_STORAGE_RESULT,
+//! MSG_KEYSTORE_RESULT, MSG_NET_RESULT) carrying that ID. Services have
+//! handled this with a `pending_ops: BTreeMap<u32, PendingOp>` per service
+//! and a state machine per operation. A [`Reactor`] keeps that state in
+//! futures instead: it maps each request ID to the task waiting on it, and
+//! runs the task again when the result arrives.
+//!
+//! ```ignore
+//! use zos_apps::{Io, Reactor};
+//!
+//! enum Done {
+//!     Loaded(Result<Option<Vec<u8>>, String>),
+//! }
+//!
+//! struct MyService {
+//!     io: Reactor<Done>,
+//! }
+//!
+//! impl MyService {
+//!     fn load(&mut self) {
+//!         let io: Io = self.io.handle();
+//!         self.io.spawn(async move {
+//!             if !io.storage_exists("settings").await.unwrap_or(false) {
+//!                 let _ = io.storage_write("settings", b"{}").await;
+//!             }
+//!             Done::Loaded(io.storage_read("settings").await)
+//!         });
+//!     }
+//!
+//!     fn on_message(&mut self, msg: Message) {
+//!         if self.io.on_message(msg.tag, &msg.data) {
+//!             while let Some(done) = self.io.next_finished() {
+//!                 // Handle the result with full access to `self`
+//!             }
+//!             return;
+//!         }
+//!         // Handle other messages
+//!     }
+//! }
+//! ```
+//!
+//! # Executor
+//!
+//! Tasks are polled when spawned and again whenever a result they wait on
+//! arrives; nothing else wakes them, so tasks may only await [`Io`] calls
+//! (directly or through `async` code that does). A finished task's output
+//! is queued until the service takes it.
+//!
+//! Results for request IDs the reactor doesn't know are left to the
+//! service, so a service can move its operations over one at a time.
+//! Storage and keystore request IDs are separate sequences, so waits are
+//! keyed by [`Source`] as well as ID.
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+use zos_ipc::keystore::MSG_KEYSTORE_RESULT;
+use zos_ipc::net::MSG_NET_RESULT;
+use zos_ipc::storage::{
+    split_result_batch, ResultKind, StorageResult, MSG_STORAGE_RESULT, MSG_STORAGE_RESULT_BATCH,
+};
+use zos_network::{HttpRequest, HttpResponse, NetworkError};
+use zos_process as syscall;
+
+/// Where a request was started, and so which message carries its result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Source {
+    /// `storage_*_async`, answered by MSG_STORAGE_RESULT(_BATCH)
+    Storage,
+    /// `keystore_*_async`, answered by MSG_KEYSTORE_RESULT
+    Keystore,
+    /// `network_fetch_async`, answered by MSG_NET_RESULT
+    Network,
+}
+
+/// A delivered result: the result type byte and its data.
+///
+/// Storage and keystore results use [`ResultKind`] values; network results
+/// use 0 for a response and anything else for a failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    pub result_type: u8,
+    pub data: Vec<u8>,
+}
+
+type Key = (Source, u32);
+
+/// State shared between the reactor and its [`Io`] handles.
+#[derive(Default)]
+struct Shared {
+    /// Results not yet taken by their wait
+    results: BTreeMap<Key, Completion>,
+    /// Task waiting on each request
+    waiters: BTreeMap<Key, u32>,
+    /// Requests being waited on, answered or not
+    pending: BTreeSet<Key>,
+    /// Requests whose wait was dropped; their results are discarded
+    abandoned: BTreeSet<Key>,
+    /// Task being polled
+    current_task: u32,
+}
+
+/// Runs tasks that wait on syscall results and collects their outputs.
+pub struct Reactor<T> {
+    shared: Rc<RefCell<Shared>>,
+    tasks: BTreeMap<u32, Pin<Box<dyn Future<Output = T>>>>,
+    finished: VecDeque<T>,
+    next_task: u32,
+}
+
+impl<T> Default for Reactor<T> {
+    fn default() -> Self {
+        Self {
+            shared: Rc::default(),
+            tasks: BTreeMap::new(),
+            finished: VecDeque::new(),
+            next_task: 1,
+        }
+    }
+}
+
+impl<T: 'static> Reactor<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A handle for making calls from tasks of this reactor.
+    pub fn handle(&self) -> Io {
+        Io {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Start a task; it runs until its first unanswered call. Returns the
+    /// task's ID.
+    pub fn spawn(&mut self, task: impl Future<Output = T> + 'static) -> u32 {
+        let id = self.next_task;
+        self.next_task = self.next_task.wrapping_add(1).max(1);
+        self.tasks.insert(id, Box::pin(task));
+        self.poll_task(id);
+        id
+    }
+
+    /// Deliver the results a message carries to the tasks waiting on them,
+    /// and run those tasks. Returns false if the message carries no result
+    /// this reactor is waiting on, leaving it to the caller.
+    ///
+    /// A batch is taken if any of its results is; results in it that the
+    /// reactor doesn't know are dropped.
+    pub fn on_message(&mut self, tag: u32, data: &[u8]) -> bool {
+        match tag {
+            MSG_STORAGE_RESULT => self.deliver(Source::Storage, data),
+            MSG_KEYSTORE_RESULT => self.deliver(Source::Keystore, data),
+            MSG_NET_RESULT => self.deliver(Source::Network, data),
+            MSG_STORAGE_RESULT_BATCH => {
+                let Some(results) = split_result_batch(data) else {
+                    return false;
+                };
+                let mut taken = false;
+                for result in results {
+                    taken |= self.deliver(Source::Storage, result);
+                }
+                taken
+            }
+            _ => false,
+        }
+    }
+
+    /// Take the output of the oldest finished task.
+    pub fn next_finished(&mut self) -> Option<T> {
+        self.finished.pop_front()
+    }
+
+    /// Tasks still running.
+    pub fn running(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Requests started and not yet answered.
+    pub fn in_flight(&self) -> usize {
+        let shared = self.shared.borrow();
+        shared.pending.len() - shared.results.len()
+    }
+
+    /// Deliver one result payload.
+    fn deliver(&mut self, source: Source, payload: &[u8]) -> bool {
+        // Network results share the storage result layout
+        let Some((request_id, result_type, data)) = split_result(payload) else {
+            return false;
+        };
+        let key = (source, request_id);
+        let waiter = {
+            let mut shared = self.shared.borrow_mut();
+            if shared.abandoned.remove(&key) {
+                return true;
+            }
+            if !shared.pending.contains(&key) {
+                return false;
+            }
+            shared.results.insert(
+                key,
+                Completion {
+                    result_type,
+                    data: data.to_vec(),
+                },
+            );
+            shared.waiters.remove(&key)
+        };
+        if let Some(task) = waiter {
+            self.poll_task(task);
+        }
+        true
+    }
+
+    fn poll_task(&mut self, id: u32) {
+        let Some(task) = self.tasks.get_mut(&id) else {
+            return;
+        };
+        self.shared.borrow_mut().current_task = id;
+        let mut cx = Context::from_waker(Waker::noop());
+        if let Poll::Ready(output) = task.as_mut().poll(&mut cx) {
+            self.tasks.remove(&id);
+            self.finished.push_back(output);
+        }
+    }
+}
+
+/// `[request_id: u32, result_type: u8, data_len: u32, data]`
+fn split_result(payload: &[u8]) -> Option<(u32, u8, &[u8])> {
+    let request_id = u32::from_le_bytes(payload.get(0..4)?.try_into().ok()?);
+    let result_type = *payload.get(4)?;
+    let len = u32::from_le_bytes(payload.get(5..9)?.try_into().ok()?) as usize;
+    let data = payload.get(9..9usize.checked_add(len)?)?;
+    Some((request_id, result_type, data))
+}
+
+/// Makes calls from tasks of a [`Reactor`]. Cheap to clone.
+#[derive(Clone)]
+pub struct Io {
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl Io {
+    /// Wait for the result of a request started with `start`, which
+    /// returns the request ID (or the syscall's error code).
+    pub fn call<E: core::fmt::Display>(
+        &self,
+        source: Source,
+        start: impl FnOnce() -> Result<u32, E>,
+    ) -> Call {
+        let key = match start() {
+            Ok(request_id) => {
+                let key = (source, request_id);
+                self.shared.borrow_mut().pending.insert(key);
+                Ok(key)
+            }
+            Err(e) => Err(format!("{:?} request failed to start: {}", source, e)),
+        };
+        Call {
+            shared: self.shared.clone(),
+            key: Some(key),
+        }
+    }
+
+    /// Read a storage key; `None` if it doesn't exist.
+    pub async fn storage_read(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let done = self
+            .call(Source::Storage, || syscall::storage_read_async(key))
+            .await?;
+        read_result(done)
+    }
+
+    /// Write a storage key.
+    pub async fn storage_write(&self, key: &str, value: &[u8]) -> Result<(), String> {
+        let done = self
+            .call(Source::Storage, || syscall::storage_write_async(key, value))
+            .await?;
+        write_result(done)
+    }
+
+    /// Delete a storage key. Deleting a missing key succeeds.
+    pub async fn storage_delete(&self, key: &str) -> Result<(), String> {
+        let done = self
+            .call(Source::Storage, || syscall::storage_delete_async(key))
+            .await?;
+        write_result(done)
+    }
+
+    /// List the storage keys under `prefix`.
+    pub async fn storage_list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        let done = self
+            .call(Source::Storage, || syscall::storage_list_async(prefix))
+            .await?;
+        list_result(done)
+    }
+
+    /// Whether a storage key exists.
+    pub async fn storage_exists(&self, key: &str) -> Result<bool, String> {
+        let done = self
+            .call(Source::Storage, || syscall::storage_exists_async(key))
+            .await?;
+        exists_result(done)
+    }
+
+    /// Read a keystore key; `None` if it doesn't exist.
+    pub async fn keystore_read(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let done = self
+            .call(Source::Keystore, || syscall::keystore_read_async(key))
+            .await?;
+        read_result(done)
+    }
+
+    /// Write a keystore key.
+    pub async fn keystore_write(&self, key: &str, value: &[u8]) -> Result<(), String> {
+        let done = self
+            .call(Source::Keystore, || {
+                syscall::keystore_write_async(key, value)
+            })
+            .await?;
+        write_result(done)
+    }
+
+    /// Delete a keystore key. Deleting a missing key succeeds.
+    pub async fn keystore_delete(&self, key: &str) -> Result<(), String> {
+        let done = self
+            .call(Source::Keystore, || syscall::keystore_delete_async(key))
+            .await?;
+        write_result(done)
+    }
+
+    /// List the keystore keys under `prefix`.
+    pub async fn keystore_list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        let done = self
+            .call(Source::Keystore, || syscall::keystore_list_async(prefix))
+            .await?;
+        list_result(done)
+    }
+
+    /// Whether a keystore key exists.
+    pub async fn keystore_exists(&self, key: &str) -> Result<bool, String> {
+        let done = self
+            .call(Source::Keystore, || syscall::keystore_exists_async(key))
+            .await?;
+        exists_result(done)
+    }
+
+    /// Make an HTTP request. Failures to start or deliver the request come
+    /// back as error responses, like the network service's own.
+    pub async fn fetch(&self, request: &HttpRequest) -> HttpResponse {
+        let json = match serde_json::to_vec(request) {
+            Ok(json) => json,
+            Err(e) => return HttpResponse::err(NetworkError::Other(format!("{}", e))),
+        };
+        let call = self.call(Source::Network, || {
+            syscall::network_fetch_async(&json).map(|request_id| request_id as u32)
+        });
+        match call.await {
+            Ok(done) if done.result_type == 0 && !done.data.is_empty() => {
+                serde_json::from_slice(&done.data).unwrap_or_else(|_| {
+                    HttpResponse::err(NetworkError::Other(String::from("Parse error")))
+                })
+            }
+            Ok(_) => HttpResponse::err(NetworkError::Other(String::from("Network error"))),
+            Err(e) => HttpResponse::err(NetworkError::Other(e)),
+        }
+    }
+}
+
+fn kind_of(done: &Completion) -> Result<ResultKind, String> {
+    let kind = ResultKind::from_u8(done.result_type)
+        .ok_or_else(|| format!("Unknown result type {}", done.result_type))?;
+    match kind {
+        ResultKind::Error => Err(String::from_utf8_lossy(&done.data).into_owned()),
+        ResultKind::Cancelled => Err(String::from("Request cancelled")),
+        kind => Ok(kind),
+    }
+}
+
+fn read_result(done: Completion) -> Result<Option<Vec<u8>>, String> {
+    match kind_of(&done)? {
+        ResultKind::ReadOk => Ok(Some(done.data)),
+        ResultKind::NotFound => Ok(None),
+        kind => Err(format!("Unexpected {} result for a read", kind.name())),
+    }
+}
+
+fn write_result(done: Completion) -> Result<(), String> {
+    match kind_of(&done)? {
+        ResultKind::WriteOk | ResultKind::NotFound => Ok(()),
+        kind => Err(format!("Unexpected {} result for a write", kind.name())),
+    }
+}
+
+fn list_result(done: Completion) -> Result<Vec<String>, String> {
+    match kind_of(&done)? {
+        ResultKind::ListOk => {
+            serde_json::from_slice(&done.data).map_err(|e| format!("Invalid key list: {}", e))
+        }
+        ResultKind::NotFound => Ok(Vec::new()),
+        kind => Err(format!("Unexpected {} result for a list", kind.name())),
+    }
+}
+
+fn exists_result(done: Completion) -> Result<bool, String> {
+    match kind_of(&done)? {
+        ResultKind::ExistsOk => {
+            Ok(StorageResult::new(0, ResultKind::ExistsOk, &done.data).exists_flag())
+        }
+        ResultKind::NotFound => Ok(false),
+        kind => Err(format!(
+            "Unexpected {} result for an exists check",
+            kind.name()
+        )),
+    }
+}
+
+/// A request in flight; resolves to its result, or to the error that kept
+/// it from starting.
+pub struct Call {
+    shared: Rc<RefCell<Shared>>,
+    /// Request, or why starting it failed; None once resolved
+    key: Option<Result<Key, String>>,
+}
+
+impl Future for Call {
+    type Output = Result<Completion, String>;
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let key = match self.key.take() {
+            Some(Ok(key)) => key,
+            Some(Err(e)) => return Poll::Ready(Err(e)),
+            None => return Poll::Ready(Err(String::from("Call polled after completion"))),
+        };
+        let mut shared = self.shared.borrow_mut();
+        if let Some(done) = shared.results.remove(&key) {
+            shared.pending.remove(&key);
+            return Poll::Ready(Ok(done));
+        }
+        let task = shared.current_task;
+        shared.waiters.insert(key, task);
+        drop(shared);
+        self.key = Some(Ok(key));
+        Poll::Pending
+    }
+}
+
+impl Drop for Call {
+    fn drop(&mut self) {
+        let Some(Ok(key)) = self.key else {
+            return;
+        };
+        let mut shared = self.shared.borrow_mut();
+        shared.waiters.remove(&key);
+        shared.pending.remove(&key);
+        if shared.results.remove(&key).is_none() {
+            shared.abandoned.insert(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn result(request_id: u32, kind: ResultKind, data: &[u8]) -> Vec<u8> {
+        StorageResult::new(request_id, kind, data).encode()
+    }
+
+    /// A request that "started" as `request_id`.
+    fn started(request_id: u32) -> impl FnOnce() -> Result<u32, i64> {
+        move || Ok(request_id)
+    }
+
+    #[test]
+    fn test_task_runs_between_results() {
+        let mut reactor = Reactor::new();
+        let io = reactor.handle();
+        reactor.spawn(async move {
+            let first = io.call(Source::Storage, started(7)).await?;
+            let second = io.call(Source::Keystore, started(7)).await?;
+            Ok::<_, String>((read_result(first)?, exists_result(second)?))
+        });
+        assert_eq!(reactor.running(), 1);
+        assert_eq!(reactor.in_flight(), 1);
+
+        // Not waited on: the same ID from another source, an unknown ID
+        let keystore = result(7, ResultKind::ExistsOk, &[1]);
+        assert!(!reactor.on_message(MSG_KEYSTORE_RESULT, &keystore));
+        assert!(!reactor.on_message(MSG_STORAGE_RESULT, &result(8, ResultKind::WriteOk, &[])));
+        assert!(!reactor.on_message(0x1000, &keystore));
+
+        assert!(reactor.on_message(MSG_STORAGE_RESULT, &result(7, ResultKind::ReadOk, b"hi")));
+        assert!(reactor.next_finished().is_none());
+        assert!(reactor.on_message(MSG_KEYSTORE_RESULT, &keystore));
+        assert_eq!(
+            reactor.next_finished(),
+            Some(Ok((Some(vec![b'h', b'i']), true)))
+        );
+        assert_eq!(reactor.running(), 0);
+        assert_eq!(reactor.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_batches_and_early_results() {
+        let mut reactor = Reactor::new();
+        let io = reactor.handle();
+        reactor.spawn(async move {
+            let a = io.call(Source::Storage, started(1));
+            let b = io.call(Source::Storage, started(2));
+            (
+                write_result(a.await.unwrap()),
+                read_result(b.await.unwrap()),
+            )
+        });
+
+        // Both answered in one batch, the second before it is awaited
+        let results = [
+            result(1, ResultKind::WriteOk, &[]),
+            result(9, ResultKind::WriteOk, &[]),
+            result(2, ResultKind::NotFound, &[]),
+        ];
+        let mut batch = (results.len() as u32).to_le_bytes().to_vec();
+        for r in &results {
+            batch.extend_from_slice(&(r.len() as u32).to_le_bytes());
+            batch.extend_from_slice(r);
+        }
+        assert!(reactor.on_message(MSG_STORAGE_RESULT_BATCH, &batch));
+        assert_eq!(reactor.next_finished(), Some((Ok(()), Ok(None))));
+        assert!(!reactor.on_message(MSG_STORAGE_RESULT_BATCH, &[1, 0]));
+    }
+
+    #[test]
+    fn test_errors_and_abandoned_calls() {
+        let mut reactor = Reactor::new();
+        let io = reactor.handle();
+
+        // A request that could not start fails without waiting
+        reactor.spawn(
+            async move { read_result(io.call(Source::Storage, || Err::<u32, _>(-4)).await?) },
+        );
+        assert!(reactor.next_finished().unwrap().is_err());
+        assert_eq!(reactor.in_flight(), 0);
+
+        // A dropped call swallows its result
+        let io = reactor.handle();
+        drop(io.call(Source::Network, started(3)));
+        assert!(reactor.on_message(MSG_NET_RESULT, &result(3, ResultKind::ReadOk, b"{}")));
+        assert!(!reactor.on_message(MSG_NET_RESULT, &result(3, ResultKind::ReadOk, b"{}")));
+
+        // Storage errors come back as strings
+        let io = reactor.handle();
+        reactor.spawn(async move { read_result(io.call(Source::Storage, started(4)).await?) });
+        assert!(reactor.on_message(
+            MSG_STORAGE_RESULT,
+            &result(4, ResultKind::Error, b"disk full")
+        ));
+        assert_eq!(
+            reactor.next_finished(),
+            Some(Err(String::from("disk full")))
+        );
+
+        // Off wasm the syscalls don't exist, so calls fail to start
+        let io = reactor.handle();
+        reactor.spawn(async move { io.storage_read("key").await });
+        assert!(reactor.next_finished().unwrap().is_err());
+    }
+}
@@ -3,6 +3,7 @@
 //! Runs inside each WASM process, providing the event loop and syscall interface.
 
 use super::app::{AppContext, ControlFlow, Message, UserContext, ZeroApp};
+use super::command::{self, Command};
 use alloc::format;
 use alloc::string::String;
 use zos_process as syscall;
@@ -79,12 +80,23 @@ impl AppRuntime {
     /// - Messages are processed before each update cycle
     /// - Updates are throttled to `update_interval_ns` (default ~60 FPS)
     /// - `shutdown()` is always called before exit (except on panic)
+    /// - The manifest's memory limit is declared before `init()`
+    /// - Manifest commands are announced once `init()` has succeeded
     ///
     /// # Failure Modes
     ///
     /// - Init failure: logs error and exits with code 1
     /// - Message handling error: logs and continues processing
     pub fn run<A: ZeroApp>(&mut self, mut app: A) -> ! {
+        if let Some(limit) = A::manifest().memory_limit {
+            if let Err(e) = syscall::set_memory_limit(self.pid, limit) {
+                syscall::debug(&format!(
+                    "[{}] could not set memory limit: {}",
+                    self.app_id, e
+                ));
+            }
+        }
+
         // Build initial context
         let ctx = self.build_context();
 
@@ -93,6 +105,7 @@ impl AppRuntime {
             syscall::debug(&format!("[{}] init failed: {}", self.app_id, e));
             syscall::exit(1);
         }
+        command::announce(A::manifest());
 
         // Main event loop
         loop {
@@ -103,8 +116,13 @@ impl AppRuntime {
             if let Some(slot) = self.input_slot {
                 // Use receive_opt for Option-based polling (NoMessage = None, errors logged)
                 while let Ok(msg) = syscall::receive(slot) {
-                    let message = Message::new(msg.tag, msg.from_pid, msg.cap_slots, msg.data);
-                    if let Err(e) = app.on_message(&ctx, message) {
+                    let message = Message::new(msg.tag, msg.from_pid, msg.cap_slots, msg.data)
+                        .with_correlation_id(msg.correlation_id);
+                    let result = match Command::from_message(&message) {
+                        Some(command) => app.on_command(&ctx, command),
+                        None => app.on_message(&ctx, message),
+                    };
+                    if let Err(e) = result {
                         syscall::debug(&format!("[{}] message error: {}", self.app_id, e));
                     }
                 }
@@ -0,0 +1,373 @@
+//! Restart-Safe Service State
+//!
+//! A service that keeps bookkeeping in memory (registries, in-flight client
+//! requests, progress of background work) loses it when its process is
+//! restarted. `StatefulService` wraps such a state struct and handles the
+//! checkpoint protocol around it:
+//!
+//! 1. On startup the service reads its checkpoint and hands the bytes (or
+//!    `None` if there is none) to [`StatefulService::restore`].
+//! 2. Mutations go through [`StatefulService::state_mut`], which marks the
+//!    state dirty.
+//! 3. From `update()`, when [`StatefulService::checkpoint_due`] says so, the
+//!    service calls [`StatefulService::begin_checkpoint`], writes the bytes
+//!    to [`StatefulService::location`], and reports the outcome with
+//!    [`StatefulService::finish_checkpoint`].
+//!
+//! The helper does no I/O itself. Services match storage responses to their
+//! own pending-operation maps, so the reads and writes must be issued (and
+//! tracked) by the service like any other operation.
+//!
+//! # Checkpoint Format
+//!
+//! ```json
+//! { "schema_version": 2, "sequence": 17, "state": { ... } }
+//! ```
+//!
+//! Checkpoints from an older schema are passed to [`ServiceState::migrate`];
+//! checkpoints from a newer schema (a downgrade) or that fail to parse are
+//! discarded and the service starts from `Default`.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::error::AppError;
+
+/// Default time between checkpoints of a dirty state.
+pub const DEFAULT_CHECKPOINT_INTERVAL_MS: u64 = 5_000;
+
+/// State that a service checkpoints across restarts.
+pub trait ServiceState: Serialize + DeserializeOwned + Default {
+    /// Version of the serialized layout. Bump it when a change would make
+    /// older checkpoints deserialize incorrectly.
+    const SCHEMA_VERSION: u32;
+
+    /// Upgrade a checkpoint written under an older schema version.
+    ///
+    /// Returning `None` discards the checkpoint.
+    fn migrate(_from_version: u32, _state: serde_json::Value) -> Option<Self> {
+        None
+    }
+}
+
+/// Where a checkpoint is kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateStore {
+    /// A system-only VFS file, written through VfsService
+    Vfs,
+    /// A keystore entry, for state that includes key material
+    Keystore,
+    /// A raw storage key, for VfsService itself (which cannot use VFS IPC)
+    Storage,
+}
+
+/// Outcome of restoring a checkpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Restored {
+    /// No checkpoint existed
+    Fresh,
+    /// The checkpoint was loaded as-is
+    Resumed { sequence: u64 },
+    /// The checkpoint was upgraded from an older schema
+    Migrated { from_version: u32 },
+    /// The checkpoint was unusable and the state was reset
+    Discarded,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Checkpoint<T> {
+    schema_version: u32,
+    sequence: u64,
+    state: T,
+}
+
+/// A service's checkpointed state and its checkpoint schedule.
+pub struct StatefulService<S: ServiceState> {
+    name: &'static str,
+    store: StateStore,
+    interval_ms: u64,
+    state: S,
+    /// Sequence number of the last checkpoint written (or restored)
+    sequence: u64,
+    restored: bool,
+    dirty: bool,
+    in_flight: bool,
+    last_checkpoint_ms: u64,
+}
+
+impl<S: ServiceState> StatefulService<S> {
+    /// Create the helper for the service `name`, with default state.
+    pub fn new(name: &'static str, store: StateStore) -> Self {
+        Self {
+            name,
+            store,
+            interval_ms: DEFAULT_CHECKPOINT_INTERVAL_MS,
+            state: S::default(),
+            sequence: 0,
+            restored: false,
+            dirty: false,
+            in_flight: false,
+            last_checkpoint_ms: 0,
+        }
+    }
+
+    /// Set the minimum time between checkpoints.
+    pub fn with_interval(mut self, interval_ms: u64) -> Self {
+        self.interval_ms = interval_ms;
+        self
+    }
+
+    /// The VFS path, keystore path or storage key the checkpoint lives at.
+    pub fn location(&self) -> String {
+        match self.store {
+            StateStore::Vfs => format!("/system/services/{}.state.json", self.name),
+            StateStore::Keystore => format!("/keys/system/{}.state.json", self.name),
+            StateStore::Storage => format!("state:{}", self.name),
+        }
+    }
+
+    /// Where the checkpoint is kept.
+    pub fn store(&self) -> StateStore {
+        self.store
+    }
+
+    /// The current state.
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Mutable access to the state; marks it for the next checkpoint.
+    pub fn state_mut(&mut self) -> &mut S {
+        self.dirty = true;
+        &mut self.state
+    }
+
+    /// Whether `restore` has run.
+    ///
+    /// No checkpoint is written before then, so a slow read cannot be
+    /// overwritten with an empty state.
+    pub fn is_restored(&self) -> bool {
+        self.restored
+    }
+
+    /// Whether the state changed since the last checkpoint.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Whether a checkpoint write is outstanding.
+    pub fn is_checkpoint_in_flight(&self) -> bool {
+        self.in_flight
+    }
+
+    /// Load the checkpoint read at startup (`None` if there was none).
+    ///
+    /// Changes made through `state_mut` before the checkpoint arrived are
+    /// replaced.
+    pub fn restore(&mut self, data: Option<&[u8]>) -> Restored {
+        self.restored = true;
+        let Some(data) = data else {
+            return Restored::Fresh;
+        };
+
+        let checkpoint: Checkpoint<serde_json::Value> = match serde_json::from_slice(data) {
+            Ok(checkpoint) => checkpoint,
+            Err(_) => return self.discard(),
+        };
+        let Checkpoint {
+            schema_version,
+            sequence,
+            state,
+        } = checkpoint;
+
+        if schema_version == S::SCHEMA_VERSION {
+            match serde_json::from_value(state) {
+                Ok(state) => {
+                    self.state = state;
+                    self.sequence = sequence;
+                    self.dirty = false;
+                    Restored::Resumed { sequence }
+                }
+                Err(_) => self.discard(),
+            }
+        } else if schema_version < S::SCHEMA_VERSION {
+            match S::migrate(schema_version, state) {
+                Some(state) => {
+                    self.state = state;
+                    self.sequence = sequence;
+                    // Rewrite in the current layout
+                    self.dirty = true;
+                    Restored::Migrated {
+                        from_version: schema_version,
+                    }
+                }
+                None => self.discard(),
+            }
+        } else {
+            self.discard()
+        }
+    }
+
+    fn discard(&mut self) -> Restored {
+        self.state = S::default();
+        // Overwrite the unusable checkpoint
+        self.dirty = true;
+        Restored::Discarded
+    }
+
+    /// Whether a checkpoint should be written now.
+    pub fn checkpoint_due(&self, now_ms: u64) -> bool {
+        self.restored
+            && self.dirty
+            && !self.in_flight
+            && now_ms.saturating_sub(self.last_checkpoint_ms) >= self.interval_ms
+    }
+
+    /// Serialize the state for a checkpoint write.
+    ///
+    /// Ignores the interval, so it can also be used to checkpoint
+    /// immediately (e.g. before shutting down). Fails while another
+    /// checkpoint is in flight, since writes could land out of order.
+    pub fn begin_checkpoint(&mut self, now_ms: u64) -> Result<Vec<u8>, AppError> {
+        if self.in_flight {
+            return Err(AppError::Internal(format!(
+                "{}: checkpoint already in flight",
+                self.name
+            )));
+        }
+        let checkpoint = Checkpoint {
+            schema_version: S::SCHEMA_VERSION,
+            sequence: self.sequence + 1,
+            state: &self.state,
+        };
+        let data = serde_json::to_vec(&checkpoint).map_err(|e| {
+            AppError::Internal(format!("{}: checkpoint serialization failed: {}", self.name, e))
+        })?;
+        self.sequence += 1;
+        self.in_flight = true;
+        self.dirty = false;
+        self.last_checkpoint_ms = now_ms;
+        Ok(data)
+    }
+
+    /// Record the outcome of the checkpoint write.
+    ///
+    /// A failed write leaves the state dirty so the next checkpoint retries.
+    pub fn finish_checkpoint(&mut self, ok: bool) {
+        self.in_flight = false;
+        if !ok {
+            self.dirty = true;
+        }
+    }
+
+    /// Sequence number of the last checkpoint written or restored.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Registry {
+        names: Vec<String>,
+    }
+
+    impl ServiceState for Registry {
+        const SCHEMA_VERSION: u32 = 2;
+
+        fn migrate(from_version: u32, state: serde_json::Value) -> Option<Self> {
+            // v1 stored a single name
+            let name = state.get("name")?.as_str()?;
+            (from_version == 1).then(|| Registry {
+                names: vec![name.to_string()],
+            })
+        }
+    }
+
+    fn service() -> StatefulService<Registry> {
+        StatefulService::new("test", StateStore::Vfs).with_interval(1_000)
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let mut svc = service();
+        assert_eq!(svc.restore(None), Restored::Fresh);
+        svc.state_mut().names.push("vfs".to_string());
+
+        let data = svc.begin_checkpoint(5_000).unwrap();
+        svc.finish_checkpoint(true);
+        assert!(!svc.is_dirty());
+
+        let mut restarted = service();
+        assert_eq!(
+            restarted.restore(Some(&data)),
+            Restored::Resumed { sequence: 1 }
+        );
+        assert_eq!(restarted.state().names, vec!["vfs".to_string()]);
+        assert!(!restarted.is_dirty());
+    }
+
+    #[test]
+    fn test_checkpoint_schedule() {
+        let mut svc = service();
+        svc.state_mut().names.push("a".to_string());
+        // Not before the checkpoint has been read
+        assert!(!svc.checkpoint_due(10_000));
+
+        svc.restore(None);
+        svc.state_mut().names.push("a".to_string());
+        assert!(svc.checkpoint_due(10_000));
+        svc.begin_checkpoint(10_000).unwrap();
+        assert!(svc.begin_checkpoint(10_000).is_err());
+
+        // Failed writes are retried after the interval
+        svc.finish_checkpoint(false);
+        assert!(!svc.checkpoint_due(10_500));
+        assert!(svc.checkpoint_due(11_000));
+    }
+
+    #[test]
+    fn test_restore_migrates_and_discards() {
+        let mut svc = service();
+        let old = br#"{"schema_version":1,"sequence":4,"state":{"name":"identity"}}"#;
+        assert_eq!(
+            svc.restore(Some(old)),
+            Restored::Migrated { from_version: 1 }
+        );
+        assert_eq!(svc.state().names, vec!["identity".to_string()]);
+        assert!(svc.is_dirty());
+        assert_eq!(svc.sequence(), 4);
+
+        let newer = br#"{"schema_version":3,"sequence":9,"state":{}}"#;
+        let mut svc = service();
+        assert_eq!(svc.restore(Some(newer)), Restored::Discarded);
+        assert_eq!(svc.state(), &Registry::default());
+
+        let mut svc = service();
+        assert_eq!(svc.restore(Some(b"not json")), Restored::Discarded);
+    }
+
+    #[test]
+    fn test_locations() {
+        assert_eq!(
+            StatefulService::<Registry>::new("identity", StateStore::Vfs).location(),
+            "/system/services/identity.state.json"
+        );
+        assert_eq!(
+            StatefulService::<Registry>::new("identity", StateStore::Keystore).location(),
+            "/keys/system/identity.state.json"
+        );
+        assert_eq!(
+            StatefulService::<Registry>::new("vfs", StateStore::Storage).location(),
+            "state:vfs"
+        );
+    }
+}
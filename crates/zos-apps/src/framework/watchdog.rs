@@ -0,0 +1,108 @@
+//! Stuck Request Watchdog
+//!
+//! Services keep each storage or keystore request in a pending map until
+//! its result arrives. A result that never arrives (the platform lost the
+//! request) would pin the entry, and whatever waits on it, forever.
+//!
+//! [`Watchdog`] arms a periodic kernel timer (`SYS_TIMER_CREATE`) on the
+//! service's input endpoint. At each `MSG_TIMER_FIRED` the service passes
+//! it the requests pending now, and gets back those that were already
+//! pending at the previous firing: outstanding for at least a whole period.
+//! What to do with them is up to the service. Like [`Drain`] it keeps no
+//! copy of the service's own state beyond the request IDs.
+//!
+//! [`Drain`]: super::Drain
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::vec::Vec;
+use zos_process as syscall;
+
+use super::app::Message;
+use super::error::AppError;
+
+/// Default interval between checks. A request is reported stuck after one
+/// to two intervals.
+pub const DEFAULT_WATCHDOG_PERIOD_MS: u32 = 30_000;
+
+/// Finds requests outstanding across two timer firings.
+#[derive(Debug, Default)]
+pub struct Watchdog {
+    /// Kernel timer, once armed
+    timer: Option<u32>,
+    /// Requests pending at the last firing
+    seen: BTreeSet<u32>,
+}
+
+impl Watchdog {
+    /// Arm (or rearm) the timer, firing on `endpoint_slot` every
+    /// `period_ms`.
+    pub fn arm(&mut self, endpoint_slot: u32, period_ms: u32) -> Result<(), AppError> {
+        if let Some(timer) = self.timer.take() {
+            let _ = syscall::timer_cancel(timer);
+        }
+        self.seen.clear();
+        let timer = syscall::timer_create(period_ms, period_ms, endpoint_slot)
+            .map_err(|e| AppError::IpcError(format!("timer_create failed: {}", e)))?;
+        self.timer = Some(timer);
+        Ok(())
+    }
+
+    /// Whether `msg` is this watchdog's timer firing.
+    pub fn is_tick(&self, msg: &Message) -> bool {
+        msg.from_pid == 0
+            && msg.tag == syscall::MSG_TIMER_FIRED
+            && self
+                .timer
+                .is_some_and(|timer| msg.data == timer.to_le_bytes())
+    }
+
+    /// Note the requests pending now. Returns those also pending at the
+    /// previous tick, in ascending order.
+    pub fn tick(&mut self, pending: impl IntoIterator<Item = u32>) -> Vec<u32> {
+        let pending: BTreeSet<u32> = pending.into_iter().collect();
+        let stuck = pending.intersection(&self.seen).copied().collect();
+        self.seen = pending;
+        stuck
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_stuck_after_two_ticks() {
+        let mut watchdog = Watchdog::default();
+        assert!(watchdog.tick([1, 2]).is_empty());
+        // 1 completed, 3 is new: only 2 has been pending all period
+        assert_eq!(watchdog.tick([2, 3]), vec![2]);
+        assert_eq!(watchdog.tick([3, 4]), vec![3]);
+        assert!(watchdog.tick([]).is_empty());
+        assert!(watchdog.tick([4]).is_empty());
+    }
+
+    #[test]
+    fn test_is_tick() {
+        let mut watchdog = Watchdog::default();
+        let fired = |from_pid, timer: u32| {
+            Message::new(
+                syscall::MSG_TIMER_FIRED,
+                from_pid,
+                vec![],
+                timer.to_le_bytes().to_vec(),
+            )
+        };
+        // Not armed: nothing is ours
+        assert!(!watchdog.is_tick(&fired(0, 7)));
+
+        watchdog.timer = Some(7);
+        assert!(watchdog.is_tick(&fired(0, 7)));
+        assert!(!watchdog.is_tick(&fired(0, 8)));
+        assert!(
+            !watchdog.is_tick(&fired(12, 7)),
+            "only the kernel fires timers"
+        );
+    }
+}
@@ -34,8 +34,9 @@ pub mod protocol;
 
 // Re-export core types at crate root for convenience
 pub use framework::{
-    AppContext, AppError, AppManifest, AppRuntime, CapabilityRequest, ControlFlow, Message,
-    ObjectType, Permissions, ProtocolError, SessionId, UserContext, UserId, ZeroApp,
+    AppContext, AppError, AppManifest, AppRuntime, CapabilityRequest, Command, CommandDecl,
+    ControlFlow, Drain, DrainPhase, DrainStep, Io, Message, ObjectType, Permissions, ProtocolError, Reactor, Restored,
+    ServiceState, SessionId, StateStore, StatefulService, UserContext, UserId, Watchdog, ZeroApp,
     // Factory manifests
     CALCULATOR_MANIFEST, CLOCK_MANIFEST, SETTINGS_MANIFEST, TERMINAL_MANIFEST,
     // Debug helpers
@@ -43,7 +44,7 @@ pub use framework::{
 };
 
 // Re-export protocol types for convenience
-pub use protocol::{tags, type_tags, InputEvent, WireSerializable};
+pub use protocol::{tags, type_tags, InputEvent, WindowProperties, WireSerializable};
 
 // Re-export app state types (for UI/frontend consumption)
 pub use apps::{
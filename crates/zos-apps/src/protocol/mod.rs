@@ -17,10 +17,12 @@
 
 mod input;
 mod serializable;
+mod window;
 mod wire_format;
 
 pub use input::InputEvent;
 pub use serializable::WireSerializable;
+pub use window::WindowProperties;
 pub use wire_format::{
     decode_envelope, decode_optional_char, decode_string, decode_u16, decode_u32, decode_u8,
     encode_envelope, encode_optional_char, encode_string, Envelope, PROTOCOL_VERSION,
@@ -39,6 +41,7 @@ pub mod type_tags {
     pub const TYPE_CLOCK_STATE: u8 = 0x01;
     pub const TYPE_CALCULATOR_STATE: u8 = 0x02;
     pub const TYPE_SETTINGS_STATE: u8 = 0x03;
+    pub const TYPE_WINDOW_PROPERTIES: u8 = 0x04;
 
     // Input type tags
     pub const TYPE_BUTTON_PRESS: u8 = 0x10;
@@ -0,0 +1,142 @@
+//! Window Properties
+//!
+//! Sent by an app via `MSG_APP_WINDOW_PROPERTIES` to change how the desktop
+//! resizes its window at runtime, e.g. a terminal snapping to whole cells
+//! after a font change:
+//!
+//! ```ignore
+//! let props = WindowProperties::with_increments((8, 16), (24, 52));
+//! syscall::send(slot, tags::MSG_APP_WINDOW_PROPERTIES, &props.to_bytes())?;
+//! ```
+//!
+//! # Payload
+//!
+//! ```text
+//! [type_tag][aspect_w: u16][aspect_h: u16][step_w: u16][step_h: u16][base_w: u16][base_h: u16]
+//! ```
+//!
+//! A zero aspect component unlocks the ratio; a zero step disables
+//! increments on that axis.
+
+use super::type_tags::TYPE_WINDOW_PROPERTIES;
+use super::{decode_u16, WireSerializable};
+use crate::framework::ProtocolError;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Resize constraints an app requests for its window
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowProperties {
+    /// Locked aspect ratio as (width, height), e.g. (16, 9)
+    pub aspect_ratio: Option<(u16, u16)>,
+    /// Resize step in pixels as (width, height)
+    pub resize_step: Option<(u16, u16)>,
+    /// Size of the non-grid part of the window (frame, padding)
+    pub resize_base: (u16, u16),
+}
+
+impl WindowProperties {
+    /// Lock the window to an aspect ratio
+    pub fn with_aspect_ratio(width: u16, height: u16) -> Self {
+        Self {
+            aspect_ratio: Some((width, height)),
+            ..Default::default()
+        }
+    }
+
+    /// Snap the window size to `base + n * step`
+    pub fn with_increments(step: (u16, u16), base: (u16, u16)) -> Self {
+        Self {
+            resize_step: Some(step),
+            resize_base: base,
+            ..Default::default()
+        }
+    }
+
+    /// Locked width / height ratio, if any
+    pub fn aspect_ratio_value(&self) -> Option<f32> {
+        match self.aspect_ratio {
+            Some((w, h)) if w > 0 && h > 0 => Some(w as f32 / h as f32),
+            _ => None,
+        }
+    }
+}
+
+impl WireSerializable for WindowProperties {
+    const TYPE_TAG: u8 = TYPE_WINDOW_PROPERTIES;
+
+    fn encode_payload(&self) -> Vec<u8> {
+        let (aspect_w, aspect_h) = self.aspect_ratio.unwrap_or((0, 0));
+        let (step_w, step_h) = self.resize_step.unwrap_or((0, 0));
+        let (base_w, base_h) = self.resize_base;
+
+        let mut payload = vec![Self::TYPE_TAG];
+        for value in [aspect_w, aspect_h, step_w, step_h, base_w, base_h] {
+            payload.extend_from_slice(&value.to_le_bytes());
+        }
+        payload
+    }
+
+    fn decode_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        let mut cursor = 1;
+        let aspect = (
+            decode_u16(payload, &mut cursor)?,
+            decode_u16(payload, &mut cursor)?,
+        );
+        let step = (
+            decode_u16(payload, &mut cursor)?,
+            decode_u16(payload, &mut cursor)?,
+        );
+        let base = (
+            decode_u16(payload, &mut cursor)?,
+            decode_u16(payload, &mut cursor)?,
+        );
+
+        Ok(Self {
+            aspect_ratio: (aspect.0 > 0 && aspect.1 > 0).then_some(aspect),
+            resize_step: (step.0 > 0 || step.1 > 0).then_some(step),
+            resize_base: base,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_properties_roundtrip() {
+        let props = WindowProperties {
+            aspect_ratio: Some((16, 9)),
+            resize_step: Some((8, 16)),
+            resize_base: (24, 52),
+        };
+        let decoded = WindowProperties::from_bytes(&props.to_bytes()).unwrap();
+        assert_eq!(decoded, props);
+    }
+
+    #[test]
+    fn test_window_properties_zero_means_unset() {
+        let props = WindowProperties::default();
+        let decoded = WindowProperties::from_bytes(&props.to_bytes()).unwrap();
+        assert_eq!(decoded.aspect_ratio, None);
+        assert_eq!(decoded.resize_step, None);
+        assert_eq!(decoded.aspect_ratio_value(), None);
+        assert_eq!(
+            WindowProperties::with_aspect_ratio(4, 3).aspect_ratio_value(),
+            Some(4.0 / 3.0)
+        );
+    }
+
+    #[test]
+    fn test_window_properties_truncated() {
+        let mut bytes = WindowProperties::with_increments((8, 16), (0, 0)).to_bytes();
+        // Shrink the declared payload length so the last field is missing
+        bytes[2] -= 2;
+        bytes.truncate(bytes.len() - 2);
+        assert!(matches!(
+            WindowProperties::from_bytes(&bytes),
+            Err(ProtocolError::TooShort)
+        ));
+    }
+}
@@ -0,0 +1,227 @@
+//! Commit batching benchmark
+//!
+//! Compares appending every mutation as its own commit against batching
+//! the mutations of a dispatch quantum into one commit, for a few quantum
+//! sizes. Also checks that both logs replay to the same state.
+//!
+//! Run with: `cargo bench -p zos-axiom --bench commit_batching`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use zos_axiom::{
+    replay, AxiomGateway, CapSlot, CommitType, EndpointId, NotificationId, Permissions, ProcessId,
+    ReplayResult, Replayable, StateHasher,
+};
+
+/// Syscalls per run. Each logs a request and a response, so this stays
+/// under the SysLog cap and trimming the syscall log does not swamp the
+/// commit side.
+const EVENTS: usize = 4_096;
+
+/// Syscalls handled per dispatch quantum.
+const QUANTUM_SIZES: [usize; 4] = [1, 4, 16, 64];
+
+/// Runs per measurement; the fastest is reported.
+const RUNS: usize = 20;
+
+/// A typical mix: IPC sends with the occasional capability change.
+fn event(i: usize) -> CommitType {
+    match i % 8 {
+        0 => CommitType::CapInserted {
+            pid: (i % 16) as u64,
+            slot: (i % 64) as u32,
+            cap_id: i as u64,
+            object_type: 1,
+            object_id: (i % 32) as u64,
+            perms: 0x03,
+        },
+        1 => CommitType::CapRemoved {
+            pid: (i % 16) as u64,
+            slot: (i % 64) as u32,
+        },
+        _ => CommitType::MessageSent {
+            from_pid: (i % 16) as u64,
+            to_endpoint: (i % 32) as u64,
+            tag: 0x8000 + (i % 16) as u32,
+            size: 64 + i % 512,
+        },
+    }
+}
+
+/// Feed EVENTS syscalls through a gateway, one mutation each.
+fn run(quantum: Option<usize>) -> AxiomGateway {
+    let mut gateway = AxiomGateway::new(0);
+    for i in 0..EVENTS {
+        if quantum.is_some_and(|q| i % q == 0) {
+            gateway.end_batch(i as u64);
+            gateway.begin_batch();
+        }
+        gateway.syscall(1, 0x40, [0; 4], i as u64, |_, _| (0, vec![event(i)]));
+    }
+    gateway.end_batch(EVENTS as u64);
+    gateway
+}
+
+fn measure(quantum: Option<usize>) -> (Duration, usize) {
+    let mut best = Duration::MAX;
+    let mut commits = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let gateway = black_box(run(quantum));
+        best = best.min(start.elapsed());
+        commits = gateway.commitlog().len();
+    }
+    (best, commits)
+}
+
+/// Folds replayed mutations into a hash, in order.
+struct Digest(StateHasher);
+
+impl Replayable for Digest {
+    fn replay_genesis(&mut self) -> ReplayResult<()> {
+        Ok(())
+    }
+    fn replay_create_process(
+        &mut self,
+        pid: ProcessId,
+        _: ProcessId,
+        _: String,
+    ) -> ReplayResult<()> {
+        self.0.write_u64(pid);
+        Ok(())
+    }
+    fn replay_exit_process(&mut self, pid: ProcessId, _: i32) -> ReplayResult<()> {
+        self.0.write_u64(pid);
+        Ok(())
+    }
+    fn replay_process_faulted(&mut self, pid: ProcessId, _: u32, _: String) -> ReplayResult<()> {
+        self.0.write_u64(pid);
+        Ok(())
+    }
+    fn replay_set_priority(&mut self, pid: ProcessId, priority: u8) -> ReplayResult<()> {
+        self.0.write_u64(pid);
+        self.0.write_u8(priority);
+        Ok(())
+    }
+    fn replay_set_memory_limit(&mut self, pid: ProcessId, limit: u64) -> ReplayResult<()> {
+        self.0.write_u64(pid);
+        self.0.write_u64(limit);
+        Ok(())
+    }
+    fn replay_set_process_group(&mut self, pid: ProcessId, pgid: ProcessId) -> ReplayResult<()> {
+        self.0.write_u64(pid);
+        self.0.write_u64(pgid);
+        Ok(())
+    }
+    fn replay_insert_capability(
+        &mut self,
+        pid: ProcessId,
+        slot: CapSlot,
+        cap_id: u64,
+        _: u8,
+        _: u64,
+        _: u8,
+    ) -> ReplayResult<()> {
+        self.0.write_u64(pid);
+        self.0.write_u32(slot);
+        self.0.write_u64(cap_id);
+        Ok(())
+    }
+    fn replay_remove_capability(&mut self, pid: ProcessId, slot: CapSlot) -> ReplayResult<()> {
+        self.0.write_u64(pid);
+        self.0.write_u32(slot);
+        Ok(())
+    }
+    fn replay_cap_granted(
+        &mut self,
+        _: ProcessId,
+        _: ProcessId,
+        _: CapSlot,
+        _: CapSlot,
+        new_cap_id: u64,
+        _: Permissions,
+    ) -> ReplayResult<()> {
+        self.0.write_u64(new_cap_id);
+        Ok(())
+    }
+    fn replay_create_endpoint(&mut self, id: EndpointId, _: ProcessId) -> ReplayResult<()> {
+        self.0.write_u64(id);
+        Ok(())
+    }
+    fn replay_destroy_endpoint(&mut self, id: EndpointId) -> ReplayResult<()> {
+        self.0.write_u64(id);
+        Ok(())
+    }
+    fn replay_message_sent(
+        &mut self,
+        from_pid: ProcessId,
+        to_endpoint: EndpointId,
+        tag: u32,
+        _: usize,
+    ) -> ReplayResult<()> {
+        self.0.write_u64(from_pid);
+        self.0.write_u64(to_endpoint);
+        self.0.write_u32(tag);
+        Ok(())
+    }
+    fn replay_create_notification(&mut self, id: NotificationId, _: ProcessId) -> ReplayResult<()> {
+        self.0.write_u64(id);
+        Ok(())
+    }
+    fn replay_destroy_notification(&mut self, id: NotificationId) -> ReplayResult<()> {
+        self.0.write_u64(id);
+        Ok(())
+    }
+    fn replay_notification_signaled(
+        &mut self,
+        from_pid: ProcessId,
+        id: NotificationId,
+        bits: u32,
+    ) -> ReplayResult<()> {
+        self.0.write_u64(from_pid);
+        self.0.write_u64(id);
+        self.0.write_u32(bits);
+        Ok(())
+    }
+    fn state_hash(&self) -> [u8; 32] {
+        self.0.finalize()
+    }
+}
+
+fn replayed_hash(gateway: &AxiomGateway) -> [u8; 32] {
+    let mut digest = Digest(StateHasher::new());
+    replay(&mut digest, gateway.commitlog().commits()).expect("replay");
+    digest.state_hash()
+}
+
+fn main() {
+    println!(
+        "commit batching: {} mutations, best of {} runs",
+        EVENTS, RUNS
+    );
+
+    let (baseline, baseline_commits) = measure(None);
+    println!(
+        "  unbatched       {:>8.2?}  {:>6} commits",
+        baseline, baseline_commits
+    );
+
+    let expected = replayed_hash(&run(None));
+    for quantum in QUANTUM_SIZES {
+        let (elapsed, commits) = measure(Some(quantum));
+        println!(
+            "  quantum {:>3}     {:>8.2?}  {:>6} commits  {:.2}x",
+            quantum,
+            elapsed,
+            commits,
+            baseline.as_secs_f64() / elapsed.as_secs_f64()
+        );
+        assert_eq!(
+            replayed_hash(&run(Some(quantum))),
+            expected,
+            "batched replay diverged at quantum {}",
+            quantum
+        );
+    }
+}
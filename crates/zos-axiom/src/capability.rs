@@ -12,9 +12,18 @@
 //!
 //! All capability verification flows through `axiom_check`, ensuring
 //! there is exactly one code path for authority verification.
+//!
+//! # Slot storage
+//!
+//! `axiom_check` runs on every IPC send and receive, so slot lookup is on
+//! the critical path. Slots are allocated upwards from 0 and processes keep
+//! their well-known endpoints in the lowest ones, so [`CapSlots`] stores the
+//! first [`DENSE_SLOTS`] slots in a vector indexed by slot number and only
+//! falls back to a BTreeMap above that.
 
-use alloc::collections::BTreeMap;
+use alloc::collections::{btree_map, BTreeMap};
 use alloc::vec::Vec;
+use core::slice;
 
 use crate::types::{CapSlot, ObjectType, Permissions};
 
@@ -42,10 +51,144 @@ impl Capability {
     }
 }
 
+/// Slots below this are looked up by index rather than in the BTreeMap.
+pub const DENSE_SLOTS: usize = 32;
+
+/// Capability slot table: a dense vector for low slots, a BTreeMap above.
+///
+/// Has the subset of the `BTreeMap<CapSlot, Capability>` API the kernel
+/// uses, and iterates in slot order like one.
+#[derive(Clone, Debug, Default)]
+pub struct CapSlots {
+    /// Slots `0..DENSE_SLOTS`, indexed by slot; each entry keeps its slot
+    /// so iteration can hand out `&CapSlot` like a map does
+    dense: Vec<Option<(CapSlot, Capability)>>,
+    /// Slots `DENSE_SLOTS..`
+    sparse: BTreeMap<CapSlot, Capability>,
+    /// Occupied dense entries
+    dense_len: usize,
+}
+
+impl CapSlots {
+    /// Create an empty slot table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a capability at `slot`, returning the one it replaced
+    pub fn insert(&mut self, slot: CapSlot, cap: Capability) -> Option<Capability> {
+        let index = slot as usize;
+        if index >= DENSE_SLOTS {
+            return self.sparse.insert(slot, cap);
+        }
+        if index >= self.dense.len() {
+            self.dense.resize_with(index + 1, || None);
+        }
+        let old = self.dense[index].replace((slot, cap)).map(|(_, cap)| cap);
+        if old.is_none() {
+            self.dense_len += 1;
+        }
+        old
+    }
+
+    /// Get the capability at `slot`
+    #[inline]
+    pub fn get(&self, slot: &CapSlot) -> Option<&Capability> {
+        let index = *slot as usize;
+        if index < DENSE_SLOTS {
+            self.dense.get(index)?.as_ref().map(|(_, cap)| cap)
+        } else {
+            self.sparse.get(slot)
+        }
+    }
+
+    /// Get the capability at `slot` mutably
+    pub fn get_mut(&mut self, slot: &CapSlot) -> Option<&mut Capability> {
+        let index = *slot as usize;
+        if index < DENSE_SLOTS {
+            self.dense.get_mut(index)?.as_mut().map(|(_, cap)| cap)
+        } else {
+            self.sparse.get_mut(slot)
+        }
+    }
+
+    /// Remove the capability at `slot`
+    pub fn remove(&mut self, slot: &CapSlot) -> Option<Capability> {
+        let index = *slot as usize;
+        if index >= DENSE_SLOTS {
+            return self.sparse.remove(slot);
+        }
+        let old = self.dense.get_mut(index)?.take().map(|(_, cap)| cap);
+        if old.is_some() {
+            self.dense_len -= 1;
+        }
+        old
+    }
+
+    /// Whether `slot` holds a capability
+    pub fn contains_key(&self, slot: &CapSlot) -> bool {
+        self.get(slot).is_some()
+    }
+
+    /// Number of occupied slots
+    pub fn len(&self) -> usize {
+        self.dense_len + self.sparse.len()
+    }
+
+    /// Whether no slot is occupied
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Occupied slots with their capabilities, in slot order
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            dense: self.dense.iter(),
+            sparse: self.sparse.iter(),
+        }
+    }
+
+    /// Occupied slots, in order
+    pub fn keys(&self) -> impl Iterator<Item = &CapSlot> {
+        self.iter().map(|(slot, _)| slot)
+    }
+
+    /// Capabilities, in slot order
+    pub fn values(&self) -> impl Iterator<Item = &Capability> {
+        self.iter().map(|(_, cap)| cap)
+    }
+}
+
+impl<'a> IntoIterator for &'a CapSlots {
+    type Item = (&'a CapSlot, &'a Capability);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over a [`CapSlots`] table, in slot order
+pub struct Iter<'a> {
+    dense: slice::Iter<'a, Option<(CapSlot, Capability)>>,
+    sparse: btree_map::Iter<'a, CapSlot, Capability>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a CapSlot, &'a Capability);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.dense.by_ref().flatten().next() {
+            Some((slot, cap)) => Some((slot, cap)),
+            None => self.sparse.next(),
+        }
+    }
+}
+
 /// Per-process capability table
 pub struct CapabilitySpace {
     /// Capability slots (public for replay)
-    pub slots: BTreeMap<CapSlot, Capability>,
+    pub slots: CapSlots,
     /// Next slot to allocate (public for replay)
     pub next_slot: CapSlot,
 }
@@ -54,7 +197,7 @@ impl CapabilitySpace {
     /// Create a new empty capability space
     pub fn new() -> Self {
         Self {
-            slots: BTreeMap::new(),
+            slots: CapSlots::new(),
             next_slot: 0,
         }
     }
@@ -68,6 +211,7 @@ impl CapabilitySpace {
     }
 
     /// Get a capability by slot
+    #[inline]
     pub fn get(&self, slot: CapSlot) -> Option<&Capability> {
         self.slots.get(&slot)
     }
@@ -311,4 +455,46 @@ mod tests {
         assert!(removed.is_some());
         assert!(cspace.is_empty());
     }
+
+    fn cap(id: u64) -> Capability {
+        Capability {
+            id,
+            object_type: ObjectType::Endpoint,
+            object_id: id,
+            permissions: Permissions::full(),
+            generation: 0,
+            expires_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_cap_slots_span_dense_and_sparse() {
+        let mut slots = CapSlots::new();
+        let boundary = DENSE_SLOTS as CapSlot;
+        for slot in [boundary + 5, 3, boundary - 1, boundary, 0] {
+            assert!(slots.insert(slot, cap(slot as u64)).is_none());
+        }
+        assert_eq!(slots.len(), 5);
+
+        // Iteration is in slot order across both halves
+        let keys: Vec<CapSlot> = slots.keys().copied().collect();
+        assert_eq!(keys, [0, 3, boundary - 1, boundary, boundary + 5]);
+        for (slot, cap) in &slots {
+            assert_eq!(cap.id, *slot as u64);
+        }
+
+        // Replacing keeps the count; removing frees the slot
+        assert_eq!(slots.insert(3, cap(30)).map(|c| c.id), Some(3));
+        assert_eq!(slots.len(), 5);
+        assert_eq!(slots.remove(&boundary).map(|c| c.id), Some(boundary as u64));
+        assert_eq!(slots.remove(&3).map(|c| c.id), Some(30));
+        assert!(slots.remove(&3).is_none());
+        assert!(!slots.contains_key(&3));
+        assert!(slots.get(&(boundary - 1)).is_some());
+        assert_eq!(slots.len(), 3);
+
+        // Lookups past the dense vector's current length miss cleanly
+        assert!(slots.get(&(boundary - 2)).is_none());
+        assert!(CapSlots::new().get(&7).is_none());
+    }
 }
@@ -14,7 +14,9 @@ use alloc::vec;
 use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
-use crate::types::{CapSlot, CommitId, EndpointId, EventId, Permissions, ProcessId};
+use crate::types::{
+    CapSlot, CommitId, EndpointId, EventId, NotificationId, Permissions, ProcessId,
+};
 
 /// A state mutation record.
 ///
@@ -61,6 +63,14 @@ pub enum CommitType {
         /// Human-readable description
         description: String,
     },
+    /// Process scheduling priority class changed
+    /// (see `zos_ipc::priority`)
+    ProcessPriorityChanged { pid: ProcessId, priority: u8 },
+    /// Process memory limit changed (bytes)
+    ProcessMemoryLimitChanged { pid: ProcessId, limit: u64 },
+    /// Process moved to another process group (the group's ID is its
+    /// leader's PID)
+    ProcessGroupChanged { pid: ProcessId, pgid: ProcessId },
 
     // === Capability Mutations ===
     /// Capability inserted into a process's CSpace
@@ -101,11 +111,45 @@ pub enum CommitType {
         /// Size of the message data in bytes
         size: usize,
     },
+
+    // === Notifications ===
+    /// Notification created
+    NotificationCreated {
+        id: NotificationId,
+        owner: ProcessId,
+    },
+    /// Notification destroyed
+    NotificationDestroyed { id: NotificationId },
+    /// Bits signalled on a notification. Pending bits are volatile like
+    /// message queues; this is for the audit trail.
+    NotificationSignaled {
+        from_pid: ProcessId,
+        id: NotificationId,
+        bits: u32,
+    },
+
+    // === Diagnostics ===
+    /// Fault injection enabled. The seed makes every injected fault
+    /// reproducible; replay treats this as a no-op.
+    FaultInjectionSeeded {
+        seed: u64,
+        /// Bitmask of enabled fault classes (see `zos_kernel::chaos`)
+        faults: u8,
+    },
+
+    // === Batching ===
+    /// Several mutations from one dispatch quantum, in the order they
+    /// happened. Replay applies them one by one, so a batch reaches the
+    /// same state as the individual commits would have.
+    Batch { events: Vec<CommitType> },
 }
 
 /// Maximum number of commits to keep in memory
 const MAX_COMMITLOG_ENTRIES: usize = 100000;
 
+/// FNV-1a prime used by the commit hash.
+const FNV_PRIME: u64 = 0x100000001b3;
+
 /// Commit log for deterministic replay.
 ///
 /// All state-changing operations are recorded as commits.
@@ -168,13 +212,29 @@ impl CommitLog {
         id
     }
 
+    /// Append the mutations of one dispatch quantum as a single commit.
+    ///
+    /// A lone mutation is appended as itself; two or more become a
+    /// `CommitType::Batch`. Returns `None` if there is nothing to append.
+    pub fn append_batch(
+        &mut self,
+        mut events: Vec<CommitType>,
+        caused_by: Option<EventId>,
+        timestamp: u64,
+    ) -> Option<CommitId> {
+        match events.len() {
+            0 => None,
+            1 => Some(self.append(events.remove(0), caused_by, timestamp)),
+            _ => Some(self.append(CommitType::Batch { events }, caused_by, timestamp)),
+        }
+    }
+
     /// Compute hash for a commit.
     ///
     /// Uses FNV-1a hash for no_std compatibility.
     /// In production, this could use SHA-256.
     fn compute_hash(commit: &Commit) -> CommitId {
         let mut hash = 0xcbf29ce484222325u64; // FNV offset basis
-        const FNV_PRIME: u64 = 0x100000001b3;
 
         // Hash prev_commit
         for byte in commit.prev_commit {
@@ -194,177 +254,7 @@ impl CommitLog {
             hash = hash.wrapping_mul(FNV_PRIME);
         }
 
-        // Hash commit_type discriminant
-        let type_byte = match &commit.commit_type {
-            CommitType::Genesis => 0u8,
-            CommitType::ProcessCreated { .. } => 1,
-            CommitType::ProcessExited { .. } => 2,
-            CommitType::ProcessFaulted { .. } => 3,
-            CommitType::CapInserted { .. } => 4,
-            CommitType::CapRemoved { .. } => 5,
-            CommitType::CapGranted { .. } => 6,
-            CommitType::EndpointCreated { .. } => 7,
-            CommitType::EndpointDestroyed { .. } => 8,
-            CommitType::MessageSent { .. } => 9,
-        };
-        hash ^= type_byte as u64;
-        hash = hash.wrapping_mul(FNV_PRIME);
-
-        // Hash additional type-specific data
-        match &commit.commit_type {
-            CommitType::Genesis => {}
-            CommitType::ProcessCreated { pid, parent, name } => {
-                for byte in pid.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in parent.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in name.bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-            }
-            CommitType::ProcessExited { pid, code } => {
-                for byte in pid.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in code.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-            }
-            CommitType::CapInserted {
-                pid,
-                slot,
-                cap_id,
-                object_type,
-                object_id,
-                perms,
-            } => {
-                for byte in pid.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in slot.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in cap_id.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                hash ^= *object_type as u64;
-                hash = hash.wrapping_mul(FNV_PRIME);
-                for byte in object_id.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                hash ^= *perms as u64;
-                hash = hash.wrapping_mul(FNV_PRIME);
-            }
-            CommitType::CapRemoved { pid, slot } => {
-                for byte in pid.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in slot.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-            }
-            CommitType::CapGranted {
-                from_pid,
-                to_pid,
-                from_slot,
-                to_slot,
-                new_cap_id,
-                perms,
-            } => {
-                for byte in from_pid.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in to_pid.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in from_slot.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in to_slot.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in new_cap_id.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                hash ^= perms.to_byte() as u64;
-                hash = hash.wrapping_mul(FNV_PRIME);
-            }
-            CommitType::EndpointCreated { id, owner } => {
-                for byte in id.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in owner.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-            }
-            CommitType::EndpointDestroyed { id } => {
-                for byte in id.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-            }
-            CommitType::ProcessFaulted {
-                pid,
-                reason,
-                description,
-            } => {
-                for byte in pid.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in reason.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in description.bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-            }
-            CommitType::MessageSent {
-                from_pid,
-                to_endpoint,
-                tag,
-                size,
-            } => {
-                for byte in from_pid.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in to_endpoint.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in tag.to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-                for byte in (*size as u64).to_le_bytes() {
-                    hash ^= byte as u64;
-                    hash = hash.wrapping_mul(FNV_PRIME);
-                }
-            }
-        }
+        hash = hash_commit_type(hash, &commit.commit_type);
 
         // Expand to 32 bytes
         let mut result = [0u8; 32];
@@ -448,6 +338,266 @@ impl CommitLog {
     }
 }
 
+/// Fold a commit type (discriminant and fields) into an FNV-1a hash.
+fn hash_commit_type(mut hash: u64, commit_type: &CommitType) -> u64 {
+    // Hash commit_type discriminant
+    let type_byte = match commit_type {
+        CommitType::Genesis => 0u8,
+        CommitType::ProcessCreated { .. } => 1,
+        CommitType::ProcessExited { .. } => 2,
+        CommitType::ProcessFaulted { .. } => 3,
+        CommitType::CapInserted { .. } => 4,
+        CommitType::CapRemoved { .. } => 5,
+        CommitType::CapGranted { .. } => 6,
+        CommitType::EndpointCreated { .. } => 7,
+        CommitType::EndpointDestroyed { .. } => 8,
+        CommitType::MessageSent { .. } => 9,
+        CommitType::FaultInjectionSeeded { .. } => 10,
+        CommitType::Batch { .. } => 11,
+        CommitType::NotificationCreated { .. } => 12,
+        CommitType::NotificationDestroyed { .. } => 13,
+        CommitType::NotificationSignaled { .. } => 14,
+        CommitType::ProcessPriorityChanged { .. } => 15,
+        CommitType::ProcessMemoryLimitChanged { .. } => 16,
+        CommitType::ProcessGroupChanged { .. } => 17,
+    };
+    hash ^= type_byte as u64;
+    hash = hash.wrapping_mul(FNV_PRIME);
+
+    // Hash additional type-specific data
+    match commit_type {
+        CommitType::Genesis => {}
+        CommitType::ProcessCreated { pid, parent, name } => {
+            for byte in pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in parent.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in name.bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::ProcessExited { pid, code } => {
+            for byte in pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in code.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::CapInserted {
+            pid,
+            slot,
+            cap_id,
+            object_type,
+            object_id,
+            perms,
+        } => {
+            for byte in pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in slot.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in cap_id.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            hash ^= *object_type as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+            for byte in object_id.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            hash ^= *perms as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+        CommitType::CapRemoved { pid, slot } => {
+            for byte in pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in slot.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::CapGranted {
+            from_pid,
+            to_pid,
+            from_slot,
+            to_slot,
+            new_cap_id,
+            perms,
+        } => {
+            for byte in from_pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in to_pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in from_slot.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in to_slot.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in new_cap_id.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            hash ^= perms.to_byte() as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+        CommitType::EndpointCreated { id, owner } => {
+            for byte in id.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in owner.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::EndpointDestroyed { id } => {
+            for byte in id.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::ProcessFaulted {
+            pid,
+            reason,
+            description,
+        } => {
+            for byte in pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in reason.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in description.bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::ProcessPriorityChanged { pid, priority } => {
+            for byte in pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            hash ^= *priority as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+        CommitType::ProcessMemoryLimitChanged { pid, limit } => {
+            for byte in pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in limit.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::ProcessGroupChanged { pid, pgid } => {
+            for byte in pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in pgid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::MessageSent {
+            from_pid,
+            to_endpoint,
+            tag,
+            size,
+        } => {
+            for byte in from_pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in to_endpoint.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in tag.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in (*size as u64).to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::NotificationCreated { id, owner } => {
+            for byte in id.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in owner.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::NotificationDestroyed { id } => {
+            for byte in id.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::NotificationSignaled { from_pid, id, bits } => {
+            for byte in from_pid.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in id.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for byte in bits.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        CommitType::FaultInjectionSeeded { seed, faults } => {
+            for byte in seed.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            hash ^= *faults as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+        CommitType::Batch { events } => {
+            for byte in (events.len() as u64).to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            for event in events {
+                hash = hash_commit_type(hash, event);
+            }
+        }
+    }
+
+    hash
+}
+
 impl Default for CommitLog {
     fn default() -> Self {
         Self::new(0)
@@ -580,4 +730,49 @@ mod tests {
 
         assert_eq!(log1.head(), log2.head());
     }
+
+    #[test]
+    fn test_commitlog_append_batch() {
+        let mut log = CommitLog::new(0);
+
+        assert!(log.append_batch(Vec::new(), None, 1000).is_none());
+        assert_eq!(log.len(), 1);
+
+        // A single event is not wrapped
+        log.append_batch(
+            vec![CommitType::EndpointCreated { id: 1, owner: 1 }],
+            None,
+            1000,
+        );
+        assert!(matches!(
+            log.commits()[1].commit_type,
+            CommitType::EndpointCreated { .. }
+        ));
+
+        let events = vec![
+            CommitType::EndpointCreated { id: 2, owner: 1 },
+            CommitType::EndpointDestroyed { id: 1 },
+        ];
+        log.append_batch(events, Some(7), 2000);
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.commits()[2].caused_by, Some(7));
+        match &log.commits()[2].commit_type {
+            CommitType::Batch { events } => assert_eq!(events.len(), 2),
+            other => panic!("expected batch, got {:?}", other),
+        }
+        assert!(log.verify_integrity());
+    }
+
+    #[test]
+    fn test_batch_hash_covers_event_order() {
+        let a = CommitType::EndpointCreated { id: 1, owner: 1 };
+        let b = CommitType::EndpointDestroyed { id: 1 };
+
+        let mut log1 = CommitLog::new(0);
+        let mut log2 = CommitLog::new(0);
+        log1.append_batch(vec![a.clone(), b.clone()], None, 1000);
+        log2.append_batch(vec![b, a], None, 1000);
+
+        assert_ne!(log1.head(), log2.head());
+    }
 }
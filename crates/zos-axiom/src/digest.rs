@@ -0,0 +1,277 @@
+//! Incremental State Digest
+//!
+//! Hashing the whole kernel state with [`StateHasher`] costs O(total state),
+//! which is too slow to do after every commit. A [`StateDigest`] instead
+//! keeps one digest per kernel object (process, capability, endpoint,
+//! notification) and
+//! combines them by wrapping addition. Addition is order-independent and
+//! invertible, so replacing one object's digest is O(log n) regardless of
+//! how much other state there is.
+//!
+//! The owner of the state refreshes the objects each commit touches (see
+//! [`touched_objects`]) right after applying it. Rebuilding a digest from
+//! scratch must always give the same result; comparing the two is how
+//! drift is detected.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::commitlog::CommitType;
+use crate::replay::StateHasher;
+use crate::types::{CapSlot, EndpointId, NotificationId, ProcessId};
+
+/// A kernel object covered by the state digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ObjectKey {
+    /// A process table entry
+    Process(ProcessId),
+    /// One slot of a process's capability space
+    Capability { pid: ProcessId, slot: CapSlot },
+    /// An IPC endpoint
+    Endpoint(EndpointId),
+    /// A notification
+    Notification(NotificationId),
+}
+
+impl ObjectKey {
+    /// Start an object digest: the key goes in first, so equal contents
+    /// under different keys never cancel out.
+    pub fn hasher(&self) -> StateHasher {
+        let mut hasher = StateHasher::new();
+        match *self {
+            ObjectKey::Process(pid) => {
+                hasher.write_u8(1);
+                hasher.write_u64(pid);
+            }
+            ObjectKey::Capability { pid, slot } => {
+                hasher.write_u8(2);
+                hasher.write_u64(pid);
+                hasher.write_u32(slot);
+            }
+            ObjectKey::Endpoint(id) => {
+                hasher.write_u8(3);
+                hasher.write_u64(id);
+            }
+            ObjectKey::Notification(id) => {
+                hasher.write_u8(4);
+                hasher.write_u64(id);
+            }
+        }
+        hasher
+    }
+}
+
+/// Per-object digests combined into one state hash.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateDigest {
+    objects: BTreeMap<ObjectKey, u64>,
+    sum: u64,
+}
+
+impl StateDigest {
+    /// Digest of the empty state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set an object's digest, or remove the object with `None`.
+    pub fn update(&mut self, key: ObjectKey, digest: Option<u64>) {
+        let old = match digest {
+            Some(digest) => {
+                let mixed = mix(digest);
+                self.sum = self.sum.wrapping_add(mixed);
+                self.objects.insert(key, mixed)
+            }
+            None => self.objects.remove(&key),
+        };
+        if let Some(old) = old {
+            self.sum = self.sum.wrapping_sub(old);
+        }
+    }
+
+    /// Remove every capability slot of `pid`.
+    pub fn remove_capabilities(&mut self, pid: ProcessId) {
+        let slots: Vec<ObjectKey> = self
+            .objects
+            .range(
+                ObjectKey::Capability { pid, slot: 0 }..=ObjectKey::Capability {
+                    pid,
+                    slot: CapSlot::MAX,
+                },
+            )
+            .map(|(key, _)| *key)
+            .collect();
+        for key in slots {
+            self.update(key, None);
+        }
+    }
+
+    /// Number of objects covered.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Whether no objects are covered.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// The 32-byte state hash.
+    pub fn finalize(&self) -> [u8; 32] {
+        let mut hasher = StateHasher::new();
+        hasher.write_u64(self.objects.len() as u64);
+        hasher.write_u64(self.sum);
+        hasher.finalize()
+    }
+}
+
+/// Objects whose state a commit may change.
+///
+/// A process exit may also drop the process's capabilities; whoever
+/// refreshes a `Process` key is expected to reconcile those as well.
+pub fn touched_objects(commit_type: &CommitType, out: &mut Vec<ObjectKey>) {
+    let key = match commit_type {
+        CommitType::Genesis
+        | CommitType::MessageSent { .. }
+        | CommitType::NotificationSignaled { .. }
+        | CommitType::FaultInjectionSeeded { .. } => return,
+        CommitType::ProcessCreated { pid, .. }
+        | CommitType::ProcessExited { pid, .. }
+        | CommitType::ProcessFaulted { pid, .. }
+        | CommitType::ProcessPriorityChanged { pid, .. }
+        | CommitType::ProcessMemoryLimitChanged { pid, .. }
+        | CommitType::ProcessGroupChanged { pid, .. } => ObjectKey::Process(*pid),
+        CommitType::CapInserted { pid, slot, .. } | CommitType::CapRemoved { pid, slot } => {
+            ObjectKey::Capability {
+                pid: *pid,
+                slot: *slot,
+            }
+        }
+        CommitType::CapGranted {
+            to_pid, to_slot, ..
+        } => ObjectKey::Capability {
+            pid: *to_pid,
+            slot: *to_slot,
+        },
+        CommitType::EndpointCreated { id, .. } | CommitType::EndpointDestroyed { id } => {
+            ObjectKey::Endpoint(*id)
+        }
+        CommitType::NotificationCreated { id, .. } | CommitType::NotificationDestroyed { id } => {
+            ObjectKey::Notification(*id)
+        }
+        CommitType::Batch { events } => {
+            for event in events {
+                touched_objects(event, out);
+            }
+            return;
+        }
+    };
+    out.push(key);
+}
+
+/// Spread an FNV digest over all 64 bits (splitmix64 finalizer), so sums
+/// of similar digests do not collide.
+fn mix(mut x: u64) -> u64 {
+    x ^= x >> 30;
+    x = x.wrapping_mul(0xbf58476d1ce4e5b9);
+    x ^= x >> 27;
+    x = x.wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn digest_of(key: ObjectKey, value: u64) -> u64 {
+        let mut hasher = key.hasher();
+        hasher.write_u64(value);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_digest_is_order_independent() {
+        let a = ObjectKey::Process(1);
+        let b = ObjectKey::Endpoint(7);
+
+        let mut d1 = StateDigest::new();
+        d1.update(a, Some(digest_of(a, 10)));
+        d1.update(b, Some(digest_of(b, 20)));
+
+        let mut d2 = StateDigest::new();
+        d2.update(b, Some(digest_of(b, 20)));
+        d2.update(a, Some(digest_of(a, 10)));
+
+        assert_eq!(d1.finalize(), d2.finalize());
+    }
+
+    #[test]
+    fn test_digest_update_and_remove() {
+        let key = ObjectKey::Capability { pid: 1, slot: 3 };
+        let empty = StateDigest::new().finalize();
+
+        let mut digest = StateDigest::new();
+        digest.update(key, Some(digest_of(key, 1)));
+        let first = digest.finalize();
+        digest.update(key, Some(digest_of(key, 2)));
+        assert_ne!(digest.finalize(), first);
+        assert_eq!(digest.len(), 1);
+
+        // Replacing back restores the earlier hash exactly
+        digest.update(key, Some(digest_of(key, 1)));
+        assert_eq!(digest.finalize(), first);
+
+        digest.update(key, None);
+        assert_eq!(digest.finalize(), empty);
+        assert!(digest.is_empty());
+    }
+
+    #[test]
+    fn test_remove_capabilities_only_touches_one_process() {
+        let mut digest = StateDigest::new();
+        for pid in [1, 2] {
+            for slot in [0, 5, CapSlot::MAX] {
+                let key = ObjectKey::Capability { pid, slot };
+                digest.update(key, Some(digest_of(key, 0)));
+            }
+        }
+        digest.update(ObjectKey::Process(1), Some(1));
+
+        digest.remove_capabilities(1);
+        assert_eq!(digest.len(), 4);
+
+        let mut expected = StateDigest::new();
+        for slot in [0, 5, CapSlot::MAX] {
+            let key = ObjectKey::Capability { pid: 2, slot };
+            expected.update(key, Some(digest_of(key, 0)));
+        }
+        expected.update(ObjectKey::Process(1), Some(1));
+        assert_eq!(digest, expected);
+    }
+
+    #[test]
+    fn test_touched_objects_flattens_batches() {
+        let batch = CommitType::Batch {
+            events: vec![
+                CommitType::CapRemoved { pid: 4, slot: 2 },
+                CommitType::MessageSent {
+                    from_pid: 4,
+                    to_endpoint: 9,
+                    tag: 1,
+                    size: 0,
+                },
+                CommitType::EndpointDestroyed { id: 9 },
+            ],
+        };
+        let mut touched = Vec::new();
+        touched_objects(&batch, &mut touched);
+        assert_eq!(
+            touched,
+            vec![
+                ObjectKey::Capability { pid: 4, slot: 2 },
+                ObjectKey::Endpoint(9)
+            ]
+        );
+    }
+}
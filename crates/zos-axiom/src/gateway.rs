@@ -8,12 +8,34 @@
 //!
 //! This ensures all syscalls are audited and all state mutations
 //! are recorded for deterministic replay.
+//!
+//! # Commit Batching
+//!
+//! Hashing and storing a commit per mutation dominates logging cost when
+//! many syscalls run back to back. Between `begin_batch` and `end_batch`
+//! (typically one dispatch quantum) mutations are staged instead, and
+//! `end_batch` appends them as a single `CommitType::Batch` commit with
+//! the events in order. Replay applies the events one by one, so the
+//! resulting state is identical; only the number of commits and hashes
+//! shrinks. The SysLog is unaffected.
 
 use alloc::vec::Vec;
 
 use crate::commitlog::{CommitLog, CommitType};
 use crate::syslog::SysLog;
-use crate::types::{CommitId, ProcessId};
+use crate::types::{CommitId, EventId, ProcessId};
+
+/// Maximum events staged in one batch (Rule 11). A full batch is appended
+/// early and a new one started.
+pub const MAX_BATCH_EVENTS: usize = 1024;
+
+/// Mutations staged during a dispatch quantum.
+#[derive(Default)]
+struct PendingBatch {
+    events: Vec<CommitType>,
+    /// Syscall that caused the first staged event
+    caused_by: Option<EventId>,
+}
 
 /// Axiom gateway: Entry point for all syscalls.
 ///
@@ -25,6 +47,8 @@ pub struct AxiomGateway {
     syslog: SysLog,
     /// State mutation log
     commitlog: CommitLog,
+    /// Staged mutations while batching
+    batch: Option<PendingBatch>,
 }
 
 impl AxiomGateway {
@@ -36,6 +60,7 @@ impl AxiomGateway {
         Self {
             syslog: SysLog::new(),
             commitlog: CommitLog::new(timestamp),
+            batch: None,
         }
     }
 
@@ -57,7 +82,7 @@ impl AxiomGateway {
     /// # Returns
     /// Tuple of (result, commit_ids) where:
     /// - `result`: The syscall result (negative = error)
-    /// - `commit_ids`: IDs of any commits created
+    /// - `commit_ids`: IDs of any commits created (none while batching)
     ///
     /// # Type Parameters
     /// - `F`: Kernel function type that takes (syscall_num, args) and returns
@@ -84,7 +109,7 @@ impl AxiomGateway {
         // 3. Append commits to CommitLog
         let commit_ids: Vec<CommitId> = commit_types
             .into_iter()
-            .map(|ct| self.commitlog.append(ct, Some(request_id), timestamp))
+            .filter_map(|ct| self.record(ct, Some(request_id), timestamp))
             .collect();
 
         // 4. Log syscall response
@@ -120,8 +145,60 @@ impl AxiomGateway {
     ///
     /// Use for internal kernel operations that don't originate
     /// from a syscall (e.g., timer-driven cleanup).
-    pub fn append_internal_commit(&mut self, commit_type: CommitType, timestamp: u64) -> CommitId {
-        self.commitlog.append(commit_type, None, timestamp)
+    ///
+    /// Returns `None` while batching: the mutation is staged and gets
+    /// its commit from `end_batch`.
+    pub fn append_internal_commit(
+        &mut self,
+        commit_type: CommitType,
+        timestamp: u64,
+    ) -> Option<CommitId> {
+        self.record(commit_type, None, timestamp)
+    }
+
+    /// Start staging mutations into one batch commit.
+    ///
+    /// Does nothing if a batch is already open.
+    pub fn begin_batch(&mut self) {
+        if self.batch.is_none() {
+            self.batch = Some(PendingBatch::default());
+        }
+    }
+
+    /// Append the staged mutations as one commit and stop batching.
+    ///
+    /// Returns the commit ID, or `None` if nothing was staged.
+    pub fn end_batch(&mut self, timestamp: u64) -> Option<CommitId> {
+        let batch = self.batch.take()?;
+        self.commitlog
+            .append_batch(batch.events, batch.caused_by, timestamp)
+    }
+
+    /// Whether mutations are currently being staged.
+    pub fn is_batching(&self) -> bool {
+        self.batch.is_some()
+    }
+
+    /// Append a mutation, or stage it if a batch is open.
+    fn record(
+        &mut self,
+        commit_type: CommitType,
+        caused_by: Option<EventId>,
+        timestamp: u64,
+    ) -> Option<CommitId> {
+        let Some(batch) = self.batch.as_mut() else {
+            return Some(self.commitlog.append(commit_type, caused_by, timestamp));
+        };
+        if batch.events.is_empty() {
+            batch.caused_by = caused_by;
+        }
+        batch.events.push(commit_type);
+        if batch.events.len() >= MAX_BATCH_EVENTS {
+            // Keep batching, but bound the memory held by one batch
+            self.end_batch(timestamp);
+            self.begin_batch();
+        }
+        None
     }
 
     /// Verify integrity of both logs.
@@ -248,11 +325,75 @@ mod tests {
     fn test_gateway_internal_commit() {
         let mut gateway = AxiomGateway::new(0);
 
-        let commit_id =
-            gateway.append_internal_commit(CommitType::ProcessExited { pid: 1, code: 0 }, 1000);
+        let commit_id = gateway
+            .append_internal_commit(CommitType::ProcessExited { pid: 1, code: 0 }, 1000)
+            .expect("not batching");
 
         assert_ne!(commit_id, [0u8; 32]);
         assert_eq!(gateway.syslog().len(), 0); // No syscall logged
         assert_eq!(gateway.commitlog().len(), 2); // Genesis + exit
     }
+
+    #[test]
+    fn test_gateway_batches_quantum_into_one_commit() {
+        let mut gateway = AxiomGateway::new(0);
+        gateway.begin_batch();
+
+        for i in 1..=5u32 {
+            let (_, commits) = gateway.syscall(1, 0x35, [i, 0, 0, 0], 1000, |_, _| {
+                (
+                    0,
+                    alloc::vec![CommitType::EndpointCreated {
+                        id: i as u64,
+                        owner: 1
+                    }],
+                )
+            });
+            assert!(commits.is_empty());
+        }
+        assert!(gateway
+            .append_internal_commit(CommitType::EndpointDestroyed { id: 1 }, 1500)
+            .is_none());
+        assert_eq!(gateway.commitlog().len(), 1); // Nothing appended yet
+
+        let id = gateway.end_batch(2000).expect("batch commit");
+        assert!(!gateway.is_batching());
+        assert_eq!(gateway.syslog().len(), 10); // SysLog is not batched
+        assert_eq!(gateway.commitlog().len(), 2); // Genesis + batch
+        assert_eq!(gateway.commitlog().head(), id);
+
+        let batch = &gateway.commitlog().commits()[1];
+        assert_eq!(batch.caused_by, Some(0)); // First syscall's request
+        match &batch.commit_type {
+            CommitType::Batch { events } => {
+                assert_eq!(events.len(), 6);
+                assert!(matches!(events[5], CommitType::EndpointDestroyed { id: 1 }));
+            }
+            other => panic!("expected batch, got {:?}", other),
+        }
+        assert!(gateway.verify_integrity());
+    }
+
+    #[test]
+    fn test_gateway_empty_batch_appends_nothing() {
+        let mut gateway = AxiomGateway::new(0);
+        gateway.begin_batch();
+        assert!(gateway.end_batch(1000).is_none());
+        assert_eq!(gateway.commitlog().len(), 1);
+        assert!(gateway.end_batch(1000).is_none()); // Not batching
+    }
+
+    #[test]
+    fn test_gateway_full_batch_is_flushed_early() {
+        let mut gateway = AxiomGateway::new(0);
+        gateway.begin_batch();
+        for i in 0..MAX_BATCH_EVENTS as u64 + 1 {
+            gateway.append_internal_commit(CommitType::EndpointCreated { id: i, owner: 1 }, 1000);
+        }
+        assert!(gateway.is_batching());
+        assert_eq!(gateway.commitlog().len(), 2); // Genesis + first full batch
+
+        gateway.end_batch(2000);
+        assert_eq!(gateway.commitlog().len(), 3); // + the remaining event
+    }
 }
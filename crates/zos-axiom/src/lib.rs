@@ -4,6 +4,7 @@
 //! - **SysLog**: Audit trail of all syscalls (request + response)
 //! - **CommitLog**: Deterministic state mutations for replay
 //! - **AxiomGateway**: Entry point for all syscalls
+//! - **StateDigest**: Incremental state hash, updated per changed object
 //! - **Capability verification**: The `axiom_check` function for authority validation
 //!
 //! # Core Guarantee
@@ -22,17 +23,19 @@ extern crate alloc;
 
 pub mod capability;
 pub mod commitlog;
+pub mod digest;
 pub mod gateway;
 pub mod replay;
 pub mod syslog;
 pub mod types;
 
 // Re-export capability types
-pub use capability::{axiom_check, AxiomError, Capability, CapabilitySpace};
+pub use capability::{axiom_check, AxiomError, CapSlots, Capability, CapabilitySpace};
 
 // Re-export main types
 pub use commitlog::{Commit, CommitLog, CommitType};
-pub use gateway::AxiomGateway;
+pub use digest::{touched_objects, ObjectKey, StateDigest};
+pub use gateway::{AxiomGateway, MAX_BATCH_EVENTS};
 pub use replay::{
     apply_commit, replay, replay_and_verify, ReplayError, ReplayResult, Replayable, StateHasher,
 };
@@ -17,7 +17,7 @@
 use alloc::string::String;
 
 use crate::commitlog::{Commit, CommitType};
-use crate::types::{CapSlot, EndpointId, Permissions, ProcessId};
+use crate::types::{CapSlot, EndpointId, NotificationId, Permissions, ProcessId};
 
 /// Errors that can occur during replay.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -77,6 +77,15 @@ pub trait Replayable {
         description: String,
     ) -> ReplayResult<()>;
 
+    /// Change a process's priority class during replay.
+    fn replay_set_priority(&mut self, pid: ProcessId, priority: u8) -> ReplayResult<()>;
+
+    /// Change a process's memory limit during replay.
+    fn replay_set_memory_limit(&mut self, pid: ProcessId, limit: u64) -> ReplayResult<()>;
+
+    /// Move a process to another process group during replay.
+    fn replay_set_process_group(&mut self, pid: ProcessId, pgid: ProcessId) -> ReplayResult<()>;
+
     /// Insert a capability during replay.
     fn replay_insert_capability(
         &mut self,
@@ -123,15 +132,36 @@ pub trait Replayable {
         size: usize,
     ) -> ReplayResult<()>;
 
+    /// Create a notification during replay.
+    fn replay_create_notification(
+        &mut self,
+        id: NotificationId,
+        owner: ProcessId,
+    ) -> ReplayResult<()>;
+
+    /// Destroy a notification during replay.
+    fn replay_destroy_notification(&mut self, id: NotificationId) -> ReplayResult<()>;
+
+    /// Record a signal during replay.
+    ///
+    /// Note: Pending bits are not replayed (volatile), like message queues.
+    fn replay_notification_signaled(
+        &mut self,
+        from_pid: ProcessId,
+        id: NotificationId,
+        bits: u32,
+    ) -> ReplayResult<()>;
+
     /// Compute a deterministic hash of the current state.
     ///
     /// This hash covers:
-    /// - Process table (PIDs, names, states)
+    /// - Process table (PIDs, names, states, priorities, memory limits,
+    ///   process groups)
     /// - Capability spaces (all capabilities)
-    /// - Endpoints (IDs, owners)
+    /// - Endpoints and notifications (IDs, owners)
     ///
     /// Does NOT include:
-    /// - Message queues (volatile)
+    /// - Message queues and pending signals (volatile)
     /// - Metrics (non-deterministic)
     fn state_hash(&self) -> [u8; 32];
 }
@@ -149,7 +179,12 @@ pub trait Replayable {
 /// - `Ok(())`: Commit applied successfully
 /// - `Err(ReplayError)`: Error applying commit
 pub fn apply_commit<R: Replayable>(state: &mut R, commit: &Commit) -> ReplayResult<()> {
-    match &commit.commit_type {
+    apply_commit_type(state, &commit.commit_type)
+}
+
+/// Apply one state mutation (a commit's payload, or one event of a batch).
+fn apply_commit_type<R: Replayable>(state: &mut R, commit_type: &CommitType) -> ReplayResult<()> {
+    match commit_type {
         CommitType::Genesis => {
             // Genesis is implicit - kernel starts in genesis state
             state.replay_genesis()
@@ -167,6 +202,18 @@ pub fn apply_commit<R: Replayable>(state: &mut R, commit: &Commit) -> ReplayResu
             description,
         } => state.replay_process_faulted(*pid, *reason, description.clone()),
 
+        CommitType::ProcessPriorityChanged { pid, priority } => {
+            state.replay_set_priority(*pid, *priority)
+        }
+
+        CommitType::ProcessMemoryLimitChanged { pid, limit } => {
+            state.replay_set_memory_limit(*pid, *limit)
+        }
+
+        CommitType::ProcessGroupChanged { pid, pgid } => {
+            state.replay_set_process_group(*pid, *pgid)
+        }
+
         CommitType::CapInserted {
             pid,
             slot,
@@ -204,6 +251,26 @@ pub fn apply_commit<R: Replayable>(state: &mut R, commit: &Commit) -> ReplayResu
             tag,
             size,
         } => state.replay_message_sent(*from_pid, *to_endpoint, *tag, *size),
+
+        CommitType::NotificationCreated { id, owner } => {
+            state.replay_create_notification(*id, *owner)
+        }
+
+        CommitType::NotificationDestroyed { id } => state.replay_destroy_notification(*id),
+
+        CommitType::NotificationSignaled { from_pid, id, bits } => {
+            state.replay_notification_signaled(*from_pid, *id, *bits)
+        }
+
+        // Fault injection only affects what happens next, not kernel state
+        CommitType::FaultInjectionSeeded { .. } => Ok(()),
+
+        CommitType::Batch { events } => {
+            for event in events {
+                apply_commit_type(state, event)?;
+            }
+            Ok(())
+        }
     }
 }
 
@@ -320,6 +387,11 @@ impl StateHasher {
         }
     }
 
+    /// Current 64-bit hash, without expanding it.
+    pub fn finish(&self) -> u64 {
+        self.hash
+    }
+
     /// Finalize and return a 32-byte hash.
     ///
     /// The 64-bit FNV hash is expanded to 32 bytes by iteratively
@@ -390,6 +462,134 @@ mod tests {
         assert_ne!(h1.finalize(), h2.finalize());
     }
 
+    /// Records the endpoint operations it is asked to replay.
+    #[derive(Default)]
+    struct EndpointRecorder {
+        ops: alloc::vec::Vec<(bool, EndpointId)>,
+    }
+
+    impl Replayable for EndpointRecorder {
+        fn replay_genesis(&mut self) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_create_process(
+            &mut self,
+            _: ProcessId,
+            _: ProcessId,
+            _: String,
+        ) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_exit_process(&mut self, _: ProcessId, _: i32) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_process_faulted(&mut self, _: ProcessId, _: u32, _: String) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_set_priority(&mut self, _: ProcessId, _: u8) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_set_memory_limit(&mut self, _: ProcessId, _: u64) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_set_process_group(&mut self, _: ProcessId, _: ProcessId) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_insert_capability(
+            &mut self,
+            _: ProcessId,
+            _: CapSlot,
+            _: u64,
+            _: u8,
+            _: u64,
+            _: u8,
+        ) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_remove_capability(&mut self, _: ProcessId, _: CapSlot) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_cap_granted(
+            &mut self,
+            _: ProcessId,
+            _: ProcessId,
+            _: CapSlot,
+            _: CapSlot,
+            _: u64,
+            _: Permissions,
+        ) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_create_endpoint(&mut self, id: EndpointId, _: ProcessId) -> ReplayResult<()> {
+            self.ops.push((true, id));
+            Ok(())
+        }
+        fn replay_destroy_endpoint(&mut self, id: EndpointId) -> ReplayResult<()> {
+            self.ops.push((false, id));
+            Ok(())
+        }
+        fn replay_message_sent(
+            &mut self,
+            _: ProcessId,
+            _: EndpointId,
+            _: u32,
+            _: usize,
+        ) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_create_notification(
+            &mut self,
+            _: NotificationId,
+            _: ProcessId,
+        ) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_destroy_notification(&mut self, _: NotificationId) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn replay_notification_signaled(
+            &mut self,
+            _: ProcessId,
+            _: NotificationId,
+            _: u32,
+        ) -> ReplayResult<()> {
+            Ok(())
+        }
+        fn state_hash(&self) -> [u8; 32] {
+            let mut hasher = StateHasher::new();
+            for (created, id) in &self.ops {
+                hasher.write_u8(*created as u8);
+                hasher.write_u64(*id);
+            }
+            hasher.finalize()
+        }
+    }
+
+    #[test]
+    fn test_batch_replays_like_individual_commits() {
+        use crate::commitlog::CommitLog;
+
+        let events = alloc::vec![
+            CommitType::EndpointCreated { id: 1, owner: 1 },
+            CommitType::EndpointCreated { id: 2, owner: 1 },
+            CommitType::EndpointDestroyed { id: 1 },
+        ];
+
+        let mut individual = CommitLog::new(0);
+        for event in events.clone() {
+            individual.append(event, None, 1000);
+        }
+        let mut batched = CommitLog::new(0);
+        batched.append_batch(events, None, 1000);
+        assert_eq!(batched.len(), 2);
+
+        let mut expected = EndpointRecorder::default();
+        replay(&mut expected, individual.commits()).unwrap();
+        let mut actual = EndpointRecorder::default();
+        replay_and_verify(&mut actual, batched.commits(), expected.state_hash()).unwrap();
+        assert_eq!(actual.ops, alloc::vec![(true, 1), (true, 2), (false, 1)]);
+    }
+
     #[test]
     fn test_replay_error_display() {
         let err = ReplayError::ProcessNotFound(123);
@@ -17,6 +17,9 @@ pub type CapSlot = u32;
 /// Endpoint identifier
 pub type EndpointId = u64;
 
+/// Notification identifier
+pub type NotificationId = u64;
+
 /// Capability permissions (serializable)
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Permissions {
@@ -97,6 +100,10 @@ pub enum ObjectType {
     IoPort = 5,
     /// Console/debug output
     Console = 6,
+    /// Notification (signal bits)
+    Notification = 12,
+    /// Debugger (read other processes' memory and state)
+    Debugger = 13,
 }
 
 impl ObjectType {
@@ -109,6 +116,8 @@ impl ObjectType {
             4 => Some(ObjectType::Irq),
             5 => Some(ObjectType::IoPort),
             6 => Some(ObjectType::Console),
+            12 => Some(ObjectType::Notification),
+            13 => Some(ObjectType::Debugger),
             _ => None,
         }
     }
@@ -18,6 +18,7 @@ use bootloader_api::info::MemoryRegionKind as BootMemoryRegionKind;
 use bootloader_api::{entry_point, BootInfo, BootloaderConfig};
 use core::panic::PanicInfo;
 use serde::{Deserialize, Serialize};
+use zos_hal::x86_64::panic_screen::PanicScreen;
 use zos_hal::x86_64::vmm::{MemoryRegionDescriptor, MemoryRegionKind};
 use zos_hal::x86_64::X86_64Hal;
 use zos_hal::{serial_println, HAL};
@@ -31,6 +32,15 @@ pub static BOOTLOADER_CONFIG: BootloaderConfig = {
     let mut config = BootloaderConfig::new_default();
     // Request physical memory mapping
     config.mappings.physical_memory = Some(bootloader_api::config::Mapping::Dynamic);
+    // KASLR: the kernel is a static PIE, so the bootloader can load it at a
+    // random base and apply its relocations. Dynamic mappings (physical
+    // memory map, boot stack, boot info) get random addresses too; RDRAND
+    // seeds the choice when the CPU has it. Keep all of them in the kernel
+    // half so user space stays free.
+    config.mappings.aslr = true;
+    config.mappings.dynamic_range_start = Some(zos_hal::x86_64::vmm::KERNEL_SPACE_START);
+    // Boot stack size; the HAL uses the same value to report overflows
+    config.kernel_stack_size = zos_hal::x86_64::vmm::stack::KERNEL_STACK_SIZE;
     config
 };
 
@@ -65,7 +75,8 @@ fn find_terminal_pid(system: &System<X86_64Hal>) -> Option<zos_kernel::ProcessId
 /// 1. Polls for syscalls from WASM processes
 /// 2. Dispatches syscalls through the Axiom verification layer
 /// 3. Completes syscalls and resumes processes
-/// 4. Routes serial input to terminal process
+/// 4. Delivers finished async (storage) requests to the processes that made them
+/// 5. Routes serial input to terminal process
 fn run_kernel_main_loop(
     system: &mut System<X86_64Hal>,
     hal: &X86_64Hal,
@@ -102,6 +113,9 @@ fn run_kernel_main_loop(
         // Poll for serial input and route through Init to terminal
         route_serial_input_to_init(system);
 
+        // Queue MSG_TIMER_FIRED for process timers that came due
+        system.fire_timers();
+
         // Run processes with synchronous syscall handling
         // This ensures syscalls are processed immediately before the process continues
         hal.run_scheduler_with_handler(|syscall| {
@@ -163,6 +177,10 @@ fn run_kernel_main_loop(
             (result, response_data)
         });
 
+        // Storage syscalls made this pass complete synchronously; hand their
+        // results back before the next one
+        deliver_async_completions(system);
+
         // Note: removed hlt() to ensure continuous polling for serial input
         // This uses more CPU but ensures responsive input handling
     }
@@ -215,6 +233,37 @@ fn route_serial_input_to_init(system: &mut System<X86_64Hal>) {
     }
 }
 
+/// Deliver finished async requests via Init (MSG_SUPERVISOR_IPC_DELIVERY).
+///
+/// The QEMU equivalent of the browser supervisor's completion delivery: each
+/// result goes to the requesting process's input endpoint, routed by Init
+/// like any other kernel-originated message.
+fn deliver_async_completions(system: &mut System<X86_64Hal>) {
+    // MSG_SUPERVISOR_IPC_DELIVERY tag (from zos-ipc)
+    const MSG_SUPERVISOR_IPC_DELIVERY: u32 = 0x2003;
+
+    // Services receive storage results on their input endpoint (slot 1)
+    const SERVICE_INPUT_SLOT: u32 = 1;
+
+    for message in system.poll_async_completions() {
+        // Build MSG_SUPERVISOR_IPC_DELIVERY payload:
+        // [target_pid: u32, endpoint_slot: u32, tag: u32, data_len: u16, data: [u8]]
+        let mut payload = alloc::vec::Vec::with_capacity(14 + message.payload.len());
+        payload.extend_from_slice(&(message.pid.0 as u32).to_le_bytes());
+        payload.extend_from_slice(&SERVICE_INPUT_SLOT.to_le_bytes());
+        payload.extend_from_slice(&message.tag.to_le_bytes());
+        payload.extend_from_slice(&(message.payload.len() as u16).to_le_bytes());
+        payload.extend_from_slice(&message.payload);
+
+        if let Err(e) = system.inject_to_init(MSG_SUPERVISOR_IPC_DELIVERY, &payload) {
+            serial_println!(
+                "[kernel] Failed to deliver {:?} result to PID {}: {:?}",
+                message.channel, message.pid.0, e
+            );
+        }
+    }
+}
+
 /// Persist CommitLog snapshot to storage
 fn persist_commitlog(system: &System<X86_64Hal>, hal: &X86_64Hal) {
     let snapshot = CommitLogSnapshot {
@@ -253,6 +302,7 @@ fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
         .physical_memory_offset
         .into_option()
         .expect("Physical memory offset required");
+    zos_hal::x86_64::vmm::set_kernel_image_offset(boot_info.kernel_image_offset);
 
     // Convert bootloader memory map to our format
     let memory_regions: alloc::vec::Vec<MemoryRegionDescriptor> = boot_info
@@ -274,6 +324,18 @@ fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
         HAL.init(phys_mem_offset, &memory_regions);
     }
 
+    // QEMU fault tests: raise the requested fault instead of booting
+    if let Some(name) = option_env!("ZOS_FAULT_TEST") {
+        use zos_hal::x86_64::fault_test;
+        match zos_hal::x86_64::panic_screen::FaultClass::from_name(name) {
+            Some(class) => fault_test::run(class),
+            None => {
+                serial_println!("Unknown ZOS_FAULT_TEST: {}", name);
+                zos_hal::x86_64::exit_qemu(fault_test::FAULT_TEST_FAILED)
+            }
+        }
+    }
+
     // Print the boot message
     serial_println!();
     serial_println!("========================================");
@@ -295,6 +357,15 @@ fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
     }
 
     serial_println!("Physical memory offset: 0x{:X}", phys_mem_offset);
+    serial_println!(
+        "Kernel image offset: 0x{:X} (KASLR entropy: {})",
+        boot_info.kernel_image_offset,
+        if zos_hal::x86_64::random::is_supported() {
+            "RDRAND"
+        } else {
+            "no RDRAND, weak"
+        }
+    );
 
     // Print memory map summary
     serial_println!();
@@ -638,13 +709,11 @@ fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
 /// Panic handler
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
-    serial_println!();
-    serial_println!("========================================");
-    serial_println!("  KERNEL PANIC!");
-    serial_println!("========================================");
-    serial_println!();
-    serial_println!("{}", info);
-    serial_println!();
-
-    zos_hal::x86_64::halt_loop()
+    let Some(mut screen) = PanicScreen::begin("KERNEL PANIC!") else {
+        // Panicked while drawing a panic screen
+        zos_hal::x86_64::halt_loop()
+    };
+    screen.line(format_args!("{}", info));
+    screen.control_registers();
+    screen.finish(None)
 }
@@ -0,0 +1,125 @@
+//! Color filters
+//!
+//! The color-blindness filters are daltonization matrices: the colors a
+//! viewer with the deficiency can't tell apart are simulated (Machado et
+//! al. 2009, full severity), and the lost difference is shifted into the
+//! channels they can still see. Every row sums to 1, so white and greys
+//! are left alone.
+
+use serde::{Deserialize, Serialize};
+
+/// Row-major 3x3 color matrix applied to RGB
+pub type ColorMatrix = [[f32; 3]; 3];
+
+/// Identity matrix (no filter)
+pub const IDENTITY: ColorMatrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Display color filter
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorFilter {
+    /// No filter
+    #[default]
+    None,
+    /// Red-weak correction
+    Protanopia,
+    /// Green-weak correction
+    Deuteranopia,
+    /// Blue-yellow correction
+    Tritanopia,
+    /// Rec. 709 luminance
+    Grayscale,
+}
+
+impl ColorFilter {
+    /// The filter's color matrix
+    pub fn matrix(self) -> ColorMatrix {
+        match self {
+            ColorFilter::None => IDENTITY,
+            ColorFilter::Protanopia => [
+                [1.0, 0.0, 0.0],
+                [0.4789, 0.4769, 0.0442],
+                [0.5973, -0.6887, 1.0914],
+            ],
+            ColorFilter::Deuteranopia => [
+                [1.0, 0.0, 0.0],
+                [0.1628, 0.7250, 0.1122],
+                [0.4547, -0.6454, 1.1907],
+            ],
+            ColorFilter::Tritanopia => [
+                [0.7412, -0.4072, 0.6660],
+                [0.0751, 0.5852, 0.3397],
+                [0.0, 0.0, 1.0],
+            ],
+            ColorFilter::Grayscale => {
+                let luma = [0.2126, 0.7152, 0.0722];
+                [luma, luma, luma]
+            }
+        }
+    }
+
+    /// Apply the filter to an RGB color, clamped to [0, 1]
+    pub fn apply(self, rgb: [f32; 3]) -> [f32; 3] {
+        let m = self.matrix();
+        let row = |r: [f32; 3]| (r[0] * rgb[0] + r[1] * rgb[1] + r[2] * rgb[2]).clamp(0.0, 1.0);
+        [row(m[0]), row(m[1]), row(m[2])]
+    }
+
+    /// The matrix as three padded WGSL `vec4` columns (`mat3x3` layout)
+    pub fn gpu_columns(self) -> [[f32; 4]; 3] {
+        let m = self.matrix();
+        [0, 1, 2].map(|c| [m[0][c], m[1][c], m[2][c], 0.0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [ColorFilter; 5] = [
+        ColorFilter::None,
+        ColorFilter::Protanopia,
+        ColorFilter::Deuteranopia,
+        ColorFilter::Tritanopia,
+        ColorFilter::Grayscale,
+    ];
+
+    #[test]
+    fn test_filters_preserve_greys() {
+        for filter in ALL {
+            for level in [0.0, 0.5, 1.0] {
+                let out = filter.apply([level; 3]);
+                for channel in out {
+                    assert!((channel - level).abs() < 0.001, "{:?} at {}", filter, level);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_grayscale_drops_hue() {
+        let [r, g, b] = ColorFilter::Grayscale.apply([1.0, 0.0, 0.0]);
+        assert_eq!(r, g);
+        assert_eq!(g, b);
+        assert!((r - 0.2126).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_corrections_separate_red_and_green() {
+        // Pure red and pure green differ in the blue channel after correction
+        for filter in [ColorFilter::Protanopia, ColorFilter::Deuteranopia] {
+            let red = filter.apply([1.0, 0.0, 0.0]);
+            let green = filter.apply([0.0, 1.0, 0.0]);
+            assert!(red[2] - green[2] > 0.4, "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn test_gpu_columns_transpose() {
+        let columns = ColorFilter::Protanopia.gpu_columns();
+        let m = ColorFilter::Protanopia.matrix();
+        assert_eq!(columns[0], [m[0][0], m[1][0], m[2][0], 0.0]);
+        assert_eq!(columns[2][1], m[1][2]);
+        assert_eq!(ColorFilter::None.gpu_columns()[1], [0.0, 1.0, 0.0, 0.0]);
+    }
+}
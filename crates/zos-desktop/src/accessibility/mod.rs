@@ -0,0 +1,49 @@
+//! Accessibility display settings
+//!
+//! High contrast, color filters and reduced motion. The settings live in
+//! the frontend's persisted settings store and are pushed to both the
+//! engine and the background renderer:
+//!
+//! - **High contrast**: the background is darkened to near-black greys so
+//!   windows and text stand out; the frontend switches its theme to match.
+//! - **Color filter**: a color matrix applied to the background's output
+//!   (and by the frontend to the rest of the page).
+//! - **Reduced motion**: desktop crossfades and camera animations finish
+//!   immediately, and the background stops animating.
+
+mod filter;
+
+pub use filter::{ColorFilter, ColorMatrix, IDENTITY};
+
+use serde::{Deserialize, Serialize};
+
+/// Accessibility display settings
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    /// High contrast theme
+    pub high_contrast: bool,
+    /// Color filter for color-blind users
+    pub color_filter: ColorFilter,
+    /// Skip animated transitions
+    pub reduced_motion: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_json_uses_defaults() {
+        let settings: DisplaySettings =
+            serde_json::from_str(r#"{"color_filter":"deuteranopia"}"#).unwrap();
+        assert_eq!(
+            settings,
+            DisplaySettings {
+                color_filter: ColorFilter::Deuteranopia,
+                ..Default::default()
+            }
+        );
+        assert!(serde_json::from_str::<DisplaySettings>(r#"{"color_filter":"sepia"}"#).is_err());
+    }
+}
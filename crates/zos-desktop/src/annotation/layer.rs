@@ -0,0 +1,131 @@
+//! Per-desktop annotation layer
+
+use super::stroke::{Stroke, StrokeId};
+use crate::math::{Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// Tool used while annotating
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnnotationTool {
+    /// Draw strokes
+    Pen {
+        /// CSS color
+        color: String,
+        /// Width at full pressure, in canvas units
+        width: f32,
+    },
+    /// Remove whole strokes touched by the eraser
+    Eraser {
+        /// Eraser radius in canvas units
+        radius: f32,
+    },
+}
+
+/// Strokes drawn on one desktop, back to front
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationLayer {
+    strokes: Vec<Stroke>,
+    next_id: StrokeId,
+}
+
+impl AnnotationLayer {
+    /// Create an empty layer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a finished stroke on top, returning its ID
+    pub fn add(&mut self, mut stroke: Stroke) -> StrokeId {
+        self.next_id += 1;
+        stroke.id = self.next_id;
+        self.strokes.push(stroke);
+        self.next_id
+    }
+
+    /// Remove a stroke by ID
+    pub fn remove(&mut self, id: StrokeId) -> bool {
+        let before = self.strokes.len();
+        self.strokes.retain(|s| s.id != id);
+        self.strokes.len() != before
+    }
+
+    /// Remove the most recent stroke (undo)
+    pub fn pop(&mut self) -> Option<Stroke> {
+        self.strokes.pop()
+    }
+
+    /// Remove every stroke touched by a circle, returning how many
+    pub fn erase_at(&mut self, center: Vec2, radius: f32) -> usize {
+        let before = self.strokes.len();
+        self.strokes.retain(|s| !s.hits(center, radius));
+        before - self.strokes.len()
+    }
+
+    /// Remove all strokes
+    pub fn clear(&mut self) {
+        self.strokes.clear();
+    }
+
+    /// All strokes, back to front
+    #[inline]
+    pub fn strokes(&self) -> &[Stroke] {
+        &self.strokes
+    }
+
+    /// Strokes whose bounds intersect `rect`
+    pub fn visible_in(&self, rect: Rect) -> impl Iterator<Item = &Stroke> {
+        self.strokes
+            .iter()
+            .filter(move |s| s.bounds().intersects(&rect))
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.strokes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotation::StrokePoint;
+
+    fn stroke_at(x: f32) -> Stroke {
+        let mut stroke = Stroke::new("#f00", 2.0);
+        stroke.push(StrokePoint::new(Vec2::new(x, 0.0), 1.0));
+        stroke.push(StrokePoint::new(Vec2::new(x, 50.0), 1.0));
+        stroke
+    }
+
+    #[test]
+    fn test_ids_stay_unique_after_removal() {
+        let mut layer = AnnotationLayer::new();
+        let a = layer.add(stroke_at(0.0));
+        assert!(layer.remove(a));
+        let b = layer.add(stroke_at(0.0));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_erase_removes_touched_strokes() {
+        let mut layer = AnnotationLayer::new();
+        layer.add(stroke_at(0.0));
+        layer.add(stroke_at(100.0));
+
+        assert_eq!(layer.erase_at(Vec2::new(3.0, 25.0), 5.0), 1);
+        assert_eq!(layer.strokes().len(), 1);
+        assert_eq!(layer.strokes()[0].points[0].position.x, 100.0);
+    }
+
+    #[test]
+    fn test_visible_in_culls() {
+        let mut layer = AnnotationLayer::new();
+        layer.add(stroke_at(0.0));
+        layer.add(stroke_at(5000.0));
+        let visible: Vec<_> = layer
+            .visible_in(Rect::new(-100.0, -100.0, 200.0, 200.0))
+            .collect();
+        assert_eq!(visible.len(), 1);
+    }
+}
@@ -0,0 +1,16 @@
+//! Pen annotations on the infinite canvas
+//!
+//! Each desktop has an [`AnnotationLayer`] of freehand [`Stroke`]s drawn
+//! between (and over) windows, like a whiteboard. Strokes are stored in
+//! desktop-local canvas coordinates, so they stay put as the camera moves,
+//! and are persisted with the desktop.
+//!
+//! Drawing goes through `DesktopEngine::begin_stroke` / `extend_stroke` /
+//! `end_stroke` while an [`AnnotationTool`] is selected; the frontend draws
+//! the layer from `DesktopEngine::annotations` with the viewport transform.
+
+mod layer;
+mod stroke;
+
+pub use layer::{AnnotationLayer, AnnotationTool};
+pub use stroke::{Stroke, StrokeId, StrokePoint};
@@ -0,0 +1,140 @@
+//! Pen strokes in canvas coordinates
+
+use crate::math::{Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// Unique stroke identifier within a desktop's annotation layer
+pub type StrokeId = u64;
+
+/// Points closer than this (in canvas units) to the previous point are dropped
+const MIN_POINT_DISTANCE: f32 = 0.5;
+
+/// A sampled pen position
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StrokePoint {
+    /// Position in desktop-local canvas coordinates
+    pub position: Vec2,
+    /// Pen pressure, `0.0..=1.0`
+    pub pressure: f32,
+}
+
+impl StrokePoint {
+    /// Create a point, clamping pressure
+    ///
+    /// Devices without pressure report 0.5 while pressed; non-finite values
+    /// are treated the same way.
+    pub fn new(position: Vec2, pressure: f32) -> Self {
+        let pressure = if pressure.is_finite() {
+            pressure.clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+        Self { position, pressure }
+    }
+}
+
+/// A freehand stroke
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stroke {
+    pub id: StrokeId,
+    /// CSS color
+    pub color: String,
+    /// Width at full pressure, in canvas units
+    pub width: f32,
+    pub points: Vec<StrokePoint>,
+}
+
+impl Stroke {
+    /// Start an empty stroke (the ID is assigned when added to a layer)
+    pub fn new(color: &str, width: f32) -> Self {
+        Self {
+            id: 0,
+            color: color.to_string(),
+            width,
+            points: Vec::new(),
+        }
+    }
+
+    /// Append a point, skipping it if it is too close to the last one
+    ///
+    /// Returns whether the point was kept.
+    pub fn push(&mut self, point: StrokePoint) -> bool {
+        if let Some(last) = self.points.last() {
+            if last.position.distance(point.position) < MIN_POINT_DISTANCE {
+                return false;
+            }
+        }
+        self.points.push(point);
+        true
+    }
+
+    /// Bounding box including the stroke's width
+    pub fn bounds(&self) -> Rect {
+        let Some(first) = self.points.first() else {
+            return Rect::ZERO;
+        };
+        let (mut min, mut max) = (first.position, first.position);
+        for p in &self.points[1..] {
+            min = Vec2::new(min.x.min(p.position.x), min.y.min(p.position.y));
+            max = Vec2::new(max.x.max(p.position.x), max.y.max(p.position.y));
+        }
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y).expand(self.width / 2.0)
+    }
+
+    /// Whether a circle at `center` with `radius` touches the stroke
+    pub fn hits(&self, center: Vec2, radius: f32) -> bool {
+        let reach = radius + self.width / 2.0;
+        match self.points.as_slice() {
+            [] => false,
+            [only] => only.position.distance(center) <= reach,
+            points => points
+                .windows(2)
+                .any(|seg| segment_distance(center, seg[0].position, seg[1].position) <= reach),
+        }
+    }
+}
+
+/// Distance from `p` to the segment `a`-`b`
+fn segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = Vec2::new(b.x - a.x, b.y - a.y);
+    let len_sq = ab.length_squared();
+    if len_sq == 0.0 {
+        return p.distance(a);
+    }
+    let t = (((p.x - a.x) * ab.x + (p.y - a.y) * ab.y) / len_sq).clamp(0.0, 1.0);
+    p.distance(Vec2::new(a.x + ab.x * t, a.y + ab.y * t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line() -> Stroke {
+        let mut stroke = Stroke::new("#fff", 4.0);
+        stroke.push(StrokePoint::new(Vec2::new(0.0, 0.0), 0.5));
+        stroke.push(StrokePoint::new(Vec2::new(100.0, 0.0), 0.5));
+        stroke
+    }
+
+    #[test]
+    fn test_push_drops_close_points() {
+        let mut stroke = line();
+        assert!(!stroke.push(StrokePoint::new(Vec2::new(100.2, 0.0), 0.5)));
+        assert_eq!(stroke.points.len(), 2);
+    }
+
+    #[test]
+    fn test_pressure_clamped() {
+        assert_eq!(StrokePoint::new(Vec2::ZERO, 3.0).pressure, 1.0);
+        assert_eq!(StrokePoint::new(Vec2::ZERO, f32::NAN).pressure, 0.5);
+    }
+
+    #[test]
+    fn test_bounds_and_hits() {
+        let stroke = line();
+        assert_eq!(stroke.bounds(), Rect::new(-2.0, -2.0, 104.0, 4.0));
+        assert!(stroke.hits(Vec2::new(50.0, 5.0), 4.0));
+        assert!(!stroke.hits(Vec2::new(50.0, 20.0), 4.0));
+        assert!(!stroke.hits(Vec2::new(120.0, 0.0), 4.0));
+    }
+}
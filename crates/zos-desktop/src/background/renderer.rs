@@ -4,6 +4,7 @@ use super::init::*;
 use super::render::*;
 use super::types::BackgroundType;
 use super::uniforms::Uniforms;
+use crate::accessibility::DisplaySettings;
 
 /// Intermediate struct for GPU resources during initialization
 struct GpuResources {
@@ -53,6 +54,9 @@ pub struct BackgroundRenderer {
     workspace_height: f32,
     workspace_gap: f32,
     transitioning: bool,
+    display: DisplaySettings,
+    /// Animation time held while reduced motion is on (seconds)
+    frozen_time: f32,
     scene_texture: wgpu::Texture,
     scene_texture_view: wgpu::TextureView,
     scene_sampler: wgpu::Sampler,
@@ -200,6 +204,8 @@ impl BackgroundRenderer {
             workspace_height: 1080.0,
             workspace_gap: 100.0,
             transitioning: false,
+            display: DisplaySettings::default(),
+            frozen_time: 0.0,
             scene_texture: resources.scene_texture,
             scene_texture_view: resources.scene_texture_view,
             scene_sampler: resources.scene_sampler,
@@ -229,7 +235,8 @@ impl BackgroundRenderer {
             workspace_width: self.workspace_width,
             workspace_height: self.workspace_height,
             workspace_gap: self.workspace_gap,
-            _pad: [0.0, 0.0, 0.0, 0.0],
+            display: self.display_uniform(),
+            color_matrix: self.display.color_filter.gpu_columns(),
         };
 
         render_glass_static(
@@ -341,10 +348,27 @@ impl BackgroundRenderer {
         self.transitioning = in_void_or_transitioning;
     }
 
+    /// Apply accessibility display settings
+    ///
+    /// Reduced motion holds the animation at its current frame; turning it
+    /// off resumes from there rather than jumping ahead.
+    pub fn set_display_settings(&mut self, settings: DisplaySettings) {
+        let now = js_sys::Date::now();
+        if settings.reduced_motion && !self.display.reduced_motion {
+            self.frozen_time = ((now - self.start_time) / 1000.0) as f32;
+        } else if !settings.reduced_motion && self.display.reduced_motion {
+            self.start_time = now - self.frozen_time as f64 * 1000.0;
+        }
+        self.display = settings;
+    }
+
     /// Render a frame with the current background
     pub fn render(&mut self) -> Result<(), String> {
-        let now = js_sys::Date::now();
-        let elapsed = ((now - self.start_time) / 1000.0) as f32;
+        let elapsed = if self.display.reduced_motion {
+            self.frozen_time
+        } else {
+            ((js_sys::Date::now() - self.start_time) / 1000.0) as f32
+        };
 
         let uniforms = self.build_uniforms(elapsed);
         self.queue
@@ -396,10 +420,22 @@ impl BackgroundRenderer {
             workspace_width: self.workspace_width,
             workspace_height: self.workspace_height,
             workspace_gap: self.workspace_gap,
-            _pad: [0.0, 0.0, 0.0, 0.0],
+            display: self.display_uniform(),
+            color_matrix: self.display.color_filter.gpu_columns(),
         }
     }
 
+    /// Display flags as passed to the shaders
+    fn display_uniform(&self) -> [f32; 4] {
+        let flag = |on: bool| if on { 1.0 } else { 0.0 };
+        [
+            flag(self.display.high_contrast),
+            flag(self.display.reduced_motion),
+            0.0,
+            0.0,
+        ]
+    }
+
     /// Get surface texture with error handling
     fn get_surface_texture(&mut self) -> Result<wgpu::SurfaceTexture, String> {
         match self.surface.get_current_texture() {
@@ -13,7 +13,8 @@ struct Uniforms {
     workspace_width: f32,
     workspace_height: f32,
     workspace_gap: f32,
-    _pad: vec4<f32>,
+    display: vec4<f32>,
+    color_matrix: mat3x3<f32>,
 };
 
 struct VsOut {
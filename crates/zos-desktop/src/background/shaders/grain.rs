@@ -12,7 +12,8 @@ struct Uniforms {
     workspace_width: f32,
     workspace_height: f32,
     workspace_gap: f32,
-    _pad: vec4<f32>,
+    display: vec4<f32>,
+    color_matrix: mat3x3<f32>,
 };
 
 struct VsOut {
@@ -91,6 +92,15 @@ fn get_workspace_bg(index: i32) -> f32 {
     return 0.0;
 }
 
+// Accessibility: high contrast darkens to near-black greys, then the color filter
+fn apply_display(color: vec3<f32>) -> vec3<f32> {
+    var c = color;
+    if (uniforms.display.x > 0.5) {
+        c = vec3<f32>(dot(c, vec3<f32>(0.2126, 0.7152, 0.0722)) * 0.25);
+    }
+    return clamp(uniforms.color_matrix * c, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
 @fragment
 fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
     // Workspace layout from uniforms (must match Rust desktop engine)
@@ -221,6 +231,6 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
         color *= vignette;
     }
     
-    return vec4<f32>(color, 1.0);
+    return vec4<f32>(apply_display(color), 1.0);
 }
 "#;
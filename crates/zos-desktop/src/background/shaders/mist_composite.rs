@@ -1,10 +1,28 @@
 /// Mist Pass 2: Composite smoke + static glass overlay
 pub const SHADER_MIST_COMPOSITE: &str = r#"
+struct Uniforms {
+    time: f32,
+    zoom: f32,
+    resolution: vec2<f32>,
+    viewport_center: vec2<f32>,
+    workspace_count: f32,
+    active_workspace: f32,
+    workspace_backgrounds: vec4<f32>,
+    transitioning: f32,
+    workspace_width: f32,
+    workspace_height: f32,
+    workspace_gap: f32,
+    display: vec4<f32>,
+    color_matrix: mat3x3<f32>,
+};
+
 struct VsOut {
     @builtin(position) position: vec4<f32>,
     @location(0) uv: vec2<f32>,
 };
 
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+
 @group(1) @binding(0) var smoke_tex: texture_2d<f32>;
 @group(1) @binding(1) var smoke_samp: sampler;
 @group(1) @binding(2) var glass_tex: texture_2d<f32>;
@@ -20,6 +38,15 @@ fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VsOut {
     return out;
 }
 
+// Accessibility: high contrast darkens to near-black greys, then the color filter
+fn apply_display(color: vec3<f32>) -> vec3<f32> {
+    var c = color;
+    if (uniforms.display.x > 0.5) {
+        c = vec3<f32>(dot(c, vec3<f32>(0.2126, 0.7152, 0.0722)) * 0.25);
+    }
+    return clamp(uniforms.color_matrix * c, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
 @fragment
 fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
     // Sample static glass overlay (RGB = color, A = distortion)
@@ -34,6 +61,6 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
     // Composite: smoke + glass overlay (additive)
     let final_color = smoke + glass.rgb;
     
-    return vec4<f32>(clamp(final_color, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+    return vec4<f32>(apply_display(clamp(final_color, vec3<f32>(0.0), vec3<f32>(1.0))), 1.0);
 }
 "#;
@@ -1,6 +1,8 @@
+use crate::accessibility::ColorFilter;
+
 /// Uniform data sent to shaders
 /// NOTE: This struct must match WGSL alignment requirements!
-/// Total struct size must be 80 bytes (padded to 16-byte boundary).
+/// Total struct size must be 128 bytes (padded to 16-byte boundary).
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Uniforms {
@@ -15,7 +17,8 @@ pub struct Uniforms {
     pub workspace_width: f32,            // offset 52
     pub workspace_height: f32,           // offset 56
     pub workspace_gap: f32,              // offset 60
-    pub _pad: [f32; 4],                  // offset 64 - padding to 80 bytes
+    pub display: [f32; 4],               // offset 64 - high contrast, reduced motion, unused x2
+    pub color_matrix: [[f32; 4]; 3],     // offset 80 - mat3x3 columns, padded to vec4
 }
 
 impl Uniforms {
@@ -33,7 +36,8 @@ impl Uniforms {
             workspace_width: 1920.0,
             workspace_height: 1080.0,
             workspace_gap: 100.0,
-            _pad: [0.0, 0.0, 0.0, 0.0],
+            display: [0.0, 0.0, 0.0, 0.0],
+            color_matrix: ColorFilter::None.gpu_columns(),
         }
     }
 }
@@ -6,6 +6,7 @@
 //! - The `active` index is always valid (`active < desktops.len()`)
 //! - Desktop IDs are globally unique and monotonically increasing
 //! - Window IDs in a desktop are unique within that desktop
+//! - Desktop bounds follow list order: desktop `i` sits at `i * (width + gap)`
 //!
 //! ## Failure Modes
 //!
@@ -13,8 +14,9 @@
 //! - Cannot delete the last remaining desktop
 //! - Switching to an invalid index returns false and leaves state unchanged
 
-use super::{Desktop, DesktopId, PersistedDesktop};
+use super::{Desktop, DesktopId, FolderBinding, PersistedDesktop};
 use crate::math::{Camera, Rect, Size, Vec2};
+use crate::widget::WidgetId;
 use crate::window::WindowId;
 
 /// Desktop manager for managing multiple desktops
@@ -55,17 +57,7 @@ impl DesktopManager {
         self.next_id += 1;
 
         let index = self.desktops.len();
-        let x = index as f32 * (self.desktop_size.width + self.desktop_gap);
-        let half_w = self.desktop_size.width / 2.0;
-        let half_h = self.desktop_size.height / 2.0;
-        let bounds = Rect::new(
-            x - half_w,
-            -half_h,
-            self.desktop_size.width,
-            self.desktop_size.height,
-        );
-
-        let desktop = Desktop::new(id, name.to_string(), bounds);
+        let desktop = Desktop::new(id, name.to_string(), self.bounds_for_index(index));
         self.desktops.push(desktop);
 
         if self.desktops.len() == 1 {
@@ -174,11 +166,46 @@ impl DesktopManager {
         }
     }
 
+    /// Move a window from whichever desktop holds it to another desktop
+    ///
+    /// Returns false if the target desktop doesn't exist.
+    pub fn move_window_to(&mut self, window_id: WindowId, desktop_index: usize) -> bool {
+        if desktop_index >= self.desktops.len() {
+            return false;
+        }
+        self.remove_window(window_id);
+        self.desktops[desktop_index].add_window(window_id);
+        true
+    }
+
     /// Find which desktop contains a window
     pub fn desktop_containing(&self, window_id: WindowId) -> Option<&Desktop> {
         self.desktops.iter().find(|d| d.contains_window(window_id))
     }
 
+    /// Move all widgets of one desktop on top of another's
+    pub fn move_widgets(&mut self, from: usize, to: usize) {
+        if from == to || from >= self.desktops.len() || to >= self.desktops.len() {
+            return;
+        }
+        let mut widgets = std::mem::take(&mut self.desktops[from].widgets);
+        self.desktops[to].widgets.append(&mut widgets);
+    }
+
+    /// Find which desktop holds a widget
+    pub fn desktop_with_widget(&self, widget_id: WidgetId) -> Option<&Desktop> {
+        self.desktops
+            .iter()
+            .find(|d| d.widgets.get(widget_id).is_some())
+    }
+
+    /// Find which desktop holds a widget, mutably
+    pub fn desktop_with_widget_mut(&mut self, widget_id: WidgetId) -> Option<&mut Desktop> {
+        self.desktops
+            .iter_mut()
+            .find(|d| d.widgets.get(widget_id).is_some())
+    }
+
     /// Set desktop size and update all existing desktop bounds
     pub fn set_desktop_size(&mut self, size: Size) {
         if self.desktop_size.width == size.width && self.desktop_size.height == size.height {
@@ -187,12 +214,25 @@ impl DesktopManager {
 
         self.desktop_size = size;
 
-        let half_w = size.width / 2.0;
-        let half_h = size.height / 2.0;
+        self.relayout();
+    }
+
+    /// Void-view bounds of the desktop at `index`
+    fn bounds_for_index(&self, index: usize) -> Rect {
+        let size = self.desktop_size;
+        let x = index as f32 * (size.width + self.desktop_gap);
+        Rect::new(
+            x - size.width / 2.0,
+            -size.height / 2.0,
+            size.width,
+            size.height,
+        )
+    }
 
-        for (index, desktop) in self.desktops.iter_mut().enumerate() {
-            let x = index as f32 * (size.width + self.desktop_gap);
-            desktop.bounds = Rect::new(x - half_w, -half_h, size.width, size.height);
+    /// Recompute every desktop's bounds from its position in the list
+    fn relayout(&mut self) {
+        for index in 0..self.desktops.len() {
+            self.desktops[index].bounds = self.bounds_for_index(index);
         }
     }
 
@@ -215,6 +255,10 @@ impl DesktopManager {
     }
 
     /// Delete a desktop by index (cannot delete last one)
+    ///
+    /// The active index keeps pointing at the same desktop when an earlier
+    /// one is removed. Windows on the deleted desktop are dropped from it;
+    /// re-home them first.
     pub fn delete(&mut self, index: usize) -> bool {
         if self.desktops.len() <= 1 || index >= self.desktops.len() {
             return false;
@@ -222,10 +266,32 @@ impl DesktopManager {
 
         self.desktops.remove(index);
 
-        if self.active >= self.desktops.len() {
-            self.active = self.desktops.len() - 1;
+        if index < self.active || self.active >= self.desktops.len() {
+            self.active -= 1;
+        }
+        self.relayout();
+
+        true
+    }
+
+    /// Move a desktop to a new position in the list
+    ///
+    /// The active desktop stays active. Returns false if either index is
+    /// out of bounds.
+    pub fn move_desktop(&mut self, from: usize, to: usize) -> bool {
+        let count = self.desktops.len();
+        if from >= count || to >= count {
+            return false;
+        }
+        if from == to {
+            return true;
         }
 
+        let active_id = self.desktops[self.active].id;
+        let desktop = self.desktops.remove(from);
+        self.desktops.insert(to, desktop);
+        self.active = self.index_of(active_id).unwrap_or(0);
+        self.relayout();
         true
     }
 
@@ -266,6 +332,35 @@ impl DesktopManager {
         self.desktops.iter().map(PersistedDesktop::from).collect()
     }
 
+    /// Restore the desktop list from persistence
+    ///
+    /// Desktops are ordered as persisted, existing desktops are matched by
+    /// ID, and missing ones are created. Desktops not in `persisted` are
+    /// kept after the restored ones.
+    pub fn restore_from_persistence(&mut self, persisted: &[PersistedDesktop]) {
+        let active_id = self.desktops.get(self.active).map(|d| d.id);
+        let mut remaining = std::mem::take(&mut self.desktops);
+
+        for p in persisted {
+            let mut desktop = match remaining.iter().position(|d| d.id == p.id) {
+                Some(pos) => remaining.remove(pos),
+                None => Desktop::new(p.id, p.name.clone(), Rect::ZERO),
+            };
+            desktop.name = p.name.clone();
+            desktop.camera = p.camera;
+            desktop.background = p.background.clone();
+            desktop.folders = p.folders.clone();
+            desktop.annotations = p.annotations.clone();
+            desktop.widgets = p.widgets.clone();
+            self.next_id = self.next_id.max(p.id + 1);
+            self.desktops.push(desktop);
+        }
+        self.desktops.append(&mut remaining);
+
+        self.active = active_id.and_then(|id| self.index_of(id)).unwrap_or(0);
+        self.relayout();
+    }
+
     /// Import desktop settings from persistence
     pub fn import_from_persistence(&mut self, persisted: &[PersistedDesktop]) {
         for p in persisted {
@@ -273,6 +368,9 @@ impl DesktopManager {
                 d.name = p.name.clone();
                 d.camera = p.camera;
                 d.background = p.background.clone();
+                d.folders = p.folders.clone();
+                d.annotations = p.annotations.clone();
+                d.widgets = p.widgets.clone();
             }
         }
     }
@@ -288,11 +386,20 @@ impl DesktopManager {
     pub fn get_desktop_background(&self, index: usize) -> Option<String> {
         self.desktops.get(index).map(|d| d.background().to_string())
     }
+
+    /// Replace the folder bindings of a desktop by index
+    pub fn set_desktop_folders(&mut self, index: usize, folders: Vec<FolderBinding>) {
+        if let Some(desktop) = self.desktops.get_mut(index) {
+            desktop.folders = folders;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::annotation::AnnotationLayer;
+    use crate::widget::WidgetLayer;
 
     #[test]
     fn test_desktop_creation() {
@@ -376,4 +483,81 @@ mod tests {
         assert!((camera.center.y - 200.0).abs() < 0.001);
         assert!((camera.zoom - 2.0).abs() < 0.001);
     }
+
+    #[test]
+    fn test_move_window_to() {
+        let mut dm = DesktopManager::new();
+        dm.create("A");
+        dm.create("B");
+        dm.add_window_to_desktop(0, 7);
+
+        assert!(dm.move_window_to(7, 1));
+        assert!(!dm.desktops()[0].contains_window(7));
+        assert!(dm.desktops()[1].contains_window(7));
+        assert!(!dm.move_window_to(7, 5));
+        assert!(dm.desktops()[1].contains_window(7));
+    }
+
+    #[test]
+    fn test_delete_keeps_active_desktop() {
+        let mut dm = DesktopManager::new();
+        dm.create("A");
+        dm.create("B");
+        let c = dm.create("C");
+        dm.switch_to(2);
+
+        assert!(dm.delete(0));
+        assert_eq!(dm.active_desktop().id, c);
+        assert_eq!(dm.active_index(), 1);
+        // Bounds close the gap left by the deleted desktop
+        assert_eq!(dm.desktops()[0].bounds.center(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_move_desktop() {
+        let mut dm = DesktopManager::new();
+        let a = dm.create("A");
+        let b = dm.create("B");
+        let c = dm.create("C");
+        dm.switch_to(0);
+
+        assert!(dm.move_desktop(0, 2));
+        let ids: Vec<DesktopId> = dm.desktops().iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec![b, c, a]);
+        assert_eq!(dm.active_index(), 2);
+        assert_eq!(dm.desktops()[2].bounds, dm.bounds_for_index(2));
+        assert!(!dm.move_desktop(0, 3));
+    }
+
+    #[test]
+    fn test_restore_from_persistence() {
+        let mut dm = DesktopManager::new();
+        let main = dm.create("Main");
+        let persisted = vec![
+            PersistedDesktop {
+                id: 7,
+                name: "Work".to_string(),
+                camera: Camera::new(),
+                background: "mist".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
+            },
+            PersistedDesktop {
+                id: main,
+                name: "Home".to_string(),
+                camera: Camera::new(),
+                background: "grain".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
+            },
+        ];
+        dm.restore_from_persistence(&persisted);
+
+        let names: Vec<&str> = dm.desktops().iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["Work", "Home"]);
+        assert_eq!(dm.active_desktop().id, main);
+        assert_eq!(dm.create("New"), 8);
+    }
 }
@@ -3,11 +3,13 @@
 //! Provides desktop (workspace) management with multiple infinite canvases.
 
 mod manager;
+mod template;
 mod types;
 mod view_mode;
 mod void;
 
 pub use manager::DesktopManager;
+pub use template::{DesktopTemplate, FolderBinding, TemplateInstance, TemplateWindow};
 pub use types::{Desktop, PersistedDesktop};
 pub use view_mode::ViewMode;
 pub use void::VoidState;
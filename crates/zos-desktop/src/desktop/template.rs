@@ -0,0 +1,134 @@
+//! Desktop templates - a reusable desktop layout
+//!
+//! A template describes an activity ("writing", "project X"): the apps to
+//! open, where their windows go, and the VFS folders the desktop works in.
+//! Instantiating one creates a new desktop; spawning the apps' processes is
+//! left to the caller, which gets the created windows back.
+
+use crate::error::{DesktopError, DesktopResult};
+use crate::math::{Camera, Size, Vec2};
+use crate::window::WindowId;
+use serde::{Deserialize, Serialize};
+
+/// A named VFS folder bound to a desktop (e.g. `"drafts"` -> `/home/notes/drafts`)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderBinding {
+    /// Name apps look the folder up by
+    pub name: String,
+    /// Absolute VFS path
+    pub path: String,
+}
+
+impl FolderBinding {
+    /// Create a folder binding
+    pub fn new(name: &str, path: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    /// Check a set of bindings: names non-empty and unique, paths absolute
+    pub fn validate_all(folders: &[FolderBinding]) -> DesktopResult<()> {
+        for (i, folder) in folders.iter().enumerate() {
+            if folder.name.trim().is_empty() {
+                return Err(DesktopError::InvalidOperation {
+                    op: "bind_folder",
+                    reason: "folder name is empty",
+                });
+            }
+            if !folder.path.starts_with('/') {
+                return Err(DesktopError::InvalidOperation {
+                    op: "bind_folder",
+                    reason: "folder path must be absolute",
+                });
+            }
+            if folders[..i].iter().any(|f| f.name == folder.name) {
+                return Err(DesktopError::InvalidOperation {
+                    op: "bind_folder",
+                    reason: "duplicate folder name",
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A window in a desktop template
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TemplateWindow {
+    /// Application to open
+    pub app_id: String,
+    /// Window title (empty uses the app's default title)
+    #[serde(default)]
+    pub title: String,
+    /// Position in desktop-local canvas coordinates
+    pub position: Vec2,
+    /// Window size including frame
+    pub size: Size,
+}
+
+/// Layout of a desktop that can be instantiated as a new desktop
+///
+/// Captured with `DesktopEngine::desktop_template` (used by "duplicate
+/// desktop" and "save as template") or written by hand, then passed to
+/// `DesktopEngine::create_desktop_from_template`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DesktopTemplate {
+    /// Name for desktops created from this template
+    pub name: String,
+    /// Background type
+    #[serde(default)]
+    pub background: Option<String>,
+    /// Camera to start with
+    #[serde(default)]
+    pub camera: Option<Camera>,
+    /// Windows back to front
+    #[serde(default)]
+    pub windows: Vec<TemplateWindow>,
+    /// VFS folders bound to the desktop
+    #[serde(default)]
+    pub folders: Vec<FolderBinding>,
+}
+
+/// Result of instantiating a template
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemplateInstance {
+    /// Index of the new desktop
+    pub desktop: usize,
+    /// Created windows with their app IDs, in template order
+    pub windows: Vec<(WindowId, String)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_folders() {
+        let ok = vec![
+            FolderBinding::new("drafts", "/home/notes/drafts"),
+            FolderBinding::new("research", "/home/notes/research"),
+        ];
+        assert!(FolderBinding::validate_all(&ok).is_ok());
+
+        let relative = vec![FolderBinding::new("drafts", "notes/drafts")];
+        assert!(FolderBinding::validate_all(&relative).is_err());
+
+        let duplicate = vec![
+            FolderBinding::new("drafts", "/a"),
+            FolderBinding::new("drafts", "/b"),
+        ];
+        assert!(FolderBinding::validate_all(&duplicate).is_err());
+    }
+
+    #[test]
+    fn test_template_json_defaults() {
+        let template: DesktopTemplate =
+            serde_json::from_str(r#"{"name":"Writing","windows":[{"app_id":"notes","position":{"x":0,"y":0},"size":{"width":800,"height":600}}]}"#)
+                .unwrap();
+        assert_eq!(template.windows[0].title, "");
+        assert!(template.folders.is_empty());
+        assert!(template.camera.is_none());
+    }
+}
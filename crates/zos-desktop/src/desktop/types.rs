@@ -1,7 +1,9 @@
 //! Desktop struct - an isolated infinite canvas
 
-use super::DesktopId;
+use super::{DesktopId, FolderBinding};
+use crate::annotation::AnnotationLayer;
 use crate::math::{Camera, Rect, Vec2};
+use crate::widget::WidgetLayer;
 use crate::window::WindowId;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +12,9 @@ use serde::{Deserialize, Serialize};
 /// Each desktop is a self-contained environment with:
 /// - Its own set of windows (in desktop-local coordinates)
 /// - Its own camera state (center and zoom)
+/// - Named VFS folders its apps work in (e.g. a project directory)
+/// - Pen annotations drawn on the canvas
+/// - Widgets (sticky notes, clocks) below its windows
 ///
 /// The `bounds` field defines where this desktop appears in the void view,
 /// not a limit on the desktop's internal size (which is infinite).
@@ -30,6 +35,15 @@ pub struct Desktop {
     /// Background type (grain, mist, etc.)
     #[serde(default = "default_background")]
     pub background: String,
+    /// Named VFS folder bindings
+    #[serde(default)]
+    pub folders: Vec<FolderBinding>,
+    /// Pen strokes in desktop-local coordinates
+    #[serde(default)]
+    pub annotations: AnnotationLayer,
+    /// Canvas widgets, back to front
+    #[serde(default)]
+    pub widgets: WidgetLayer,
 }
 
 fn default_background() -> String {
@@ -46,6 +60,9 @@ impl Desktop {
             windows: Vec::new(),
             camera: Camera::new(),
             background: default_background(),
+            folders: Vec::new(),
+            annotations: AnnotationLayer::new(),
+            widgets: WidgetLayer::new(),
         }
     }
 
@@ -107,6 +124,14 @@ impl Desktop {
     pub fn background(&self) -> &str {
         &self.background
     }
+
+    /// Path bound to a folder name, if any
+    pub fn folder(&self, name: &str) -> Option<&str> {
+        self.folders
+            .iter()
+            .find(|f| f.name == name)
+            .map(|f| f.path.as_str())
+    }
 }
 
 /// Persisted desktop data (for storage)
@@ -118,6 +143,12 @@ pub struct PersistedDesktop {
     pub camera: Camera,
     #[serde(default = "default_background")]
     pub background: String,
+    #[serde(default)]
+    pub folders: Vec<FolderBinding>,
+    #[serde(default, skip_serializing_if = "AnnotationLayer::is_empty")]
+    pub annotations: AnnotationLayer,
+    #[serde(default, skip_serializing_if = "WidgetLayer::is_empty")]
+    pub widgets: WidgetLayer,
 }
 
 impl From<&Desktop> for PersistedDesktop {
@@ -127,6 +158,9 @@ impl From<&Desktop> for PersistedDesktop {
             name: desktop.name.clone(),
             camera: desktop.camera,
             background: desktop.background.clone(),
+            folders: desktop.folders.clone(),
+            annotations: desktop.annotations.clone(),
+            widgets: desktop.widgets.clone(),
         }
     }
 }
@@ -0,0 +1,96 @@
+//! Accessibility display settings
+//!
+//! Reduced motion is enforced here: every crossfade and camera animation
+//! starts through `start_crossfade` / `start_camera_animation`, which skip
+//! straight to the end when it is on. The transition still completes on the
+//! next tick, so view-mode changes and desktop switches behave the same,
+//! just without the animation in between.
+
+use super::DesktopEngine;
+use crate::accessibility::DisplaySettings;
+use crate::transition::{CameraAnimation, Crossfade};
+use tracing::debug;
+
+impl DesktopEngine {
+    /// Apply accessibility display settings
+    pub fn set_display_settings(&mut self, settings: DisplaySettings) {
+        debug!(?settings, "display settings changed");
+        self.display = settings;
+    }
+
+    /// Current accessibility display settings
+    #[inline]
+    pub fn display_settings(&self) -> &DisplaySettings {
+        &self.display
+    }
+
+    /// Start a crossfade, honoring reduced motion
+    pub(crate) fn start_crossfade(&mut self, mut crossfade: Crossfade, now_ms: f64) {
+        if self.display.reduced_motion {
+            crossfade.finish_now(now_ms);
+        }
+        self.crossfade = Some(crossfade);
+    }
+
+    /// Start a camera animation, honoring reduced motion
+    pub(crate) fn start_camera_animation(&mut self, mut animation: CameraAnimation, now_ms: f64) {
+        if self.display.reduced_motion {
+            animation.finish_now(now_ms);
+        }
+        self.camera_animation = Some(animation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{Size, Vec2};
+    use crate::window::WindowConfig;
+
+    fn engine(reduced_motion: bool) -> DesktopEngine {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        engine.create_desktop("Second");
+        engine.set_display_settings(DisplaySettings {
+            reduced_motion,
+            ..Default::default()
+        });
+        engine
+    }
+
+    #[test]
+    fn test_reduced_motion_skips_crossfades() {
+        let mut engine = engine(true);
+        engine.switch_desktop(1, 1000.0);
+        assert!(!engine.tick_transition(1000.0));
+        assert!(!engine.is_crossfading());
+        assert_eq!(engine.layer_opacities(1000.0), (1.0, 0.0));
+
+        engine.enter_void(1000.0);
+        engine.tick_transition(1000.0);
+        assert!(engine.view_mode.is_void());
+        assert!(!engine.is_crossfading());
+    }
+
+    #[test]
+    fn test_reduced_motion_skips_camera_animation() {
+        let mut engine = engine(true);
+        let id = engine.create_window(WindowConfig {
+            position: Some(Vec2::new(3000.0, 2000.0)),
+            size: Size::new(400.0, 300.0),
+            ..Default::default()
+        });
+        engine.zoom_to_window(id, 1000.0);
+        engine.tick_transition(1000.0);
+        assert!(engine.camera_animation.is_none());
+        assert!(engine.viewport.center.x > 3000.0);
+    }
+
+    #[test]
+    fn test_motion_animates_by_default() {
+        let mut engine = engine(false);
+        engine.switch_desktop(1, 1000.0);
+        assert!(engine.tick_transition(1000.0));
+        assert!(engine.is_crossfading());
+    }
+}
@@ -1,13 +1,30 @@
 //! Camera animation
 
 use super::DesktopEngine;
-use crate::math::Camera;
+use crate::math::{Camera, Rect};
 use crate::transition::CameraAnimation;
-use crate::window::WindowId;
+use crate::window::{WindowId, WindowState};
+use tracing::debug;
+
+/// Screen pixels kept clear around a zoom-to-fit target (also clears the taskbar)
+const FIT_PADDING: f32 = 48.0;
+
+/// Zoom range for zoom-to-fit; fitting never zooms in past 100%
+const FIT_MIN_ZOOM: f32 = 0.05;
+const FIT_MAX_ZOOM: f32 = 1.0;
 
 impl DesktopEngine {
     /// Pan the camera to center on a window
+    ///
+    /// Panning away from a fullscreen window takes it out of fullscreen.
     pub fn pan_to_window(&mut self, id: WindowId, now_ms: f64) {
+        if let Some(fullscreen) = self.fullscreen_window() {
+            if fullscreen == id {
+                return;
+            }
+            self.exit_fullscreen(fullscreen);
+        }
+
         let window = match self.windows.get(id) {
             Some(w) => w,
             None => return,
@@ -23,11 +40,129 @@ impl DesktopEngine {
             Camera::at(target_center, self.viewport.zoom)
         };
 
-        self.camera_animation = Some(CameraAnimation::new(
-            self.viewport.to_camera(),
-            target_camera,
+        self.start_camera_animation(
+            CameraAnimation::new(self.viewport.to_camera(), target_camera, now_ms),
             now_ms,
-        ));
+        );
         self.last_activity_ms = now_ms;
     }
+
+    /// Animate the camera to fit a window on screen
+    pub fn zoom_to_window(&mut self, id: WindowId, now_ms: f64) {
+        let rect = match self.windows.get(id) {
+            Some(w) if w.state != WindowState::Minimized => w.rect(),
+            _ => return,
+        };
+        self.zoom_to_rect(rect, now_ms);
+    }
+
+    /// Animate the camera to fit every visible window on the active desktop
+    ///
+    /// With no windows open, the desktop itself is shown.
+    pub fn zoom_to_fit_all(&mut self, now_ms: f64) {
+        let desktop = self.desktops.active_desktop();
+        let rect = self
+            .windows
+            .all_windows()
+            .filter(|w| desktop.contains_window(w.id) && w.state != WindowState::Minimized)
+            .map(|w| w.rect())
+            .reduce(|acc, r| acc.union(&r))
+            .unwrap_or(desktop.bounds);
+        self.zoom_to_rect(rect, now_ms);
+    }
+
+    /// Animate the camera to fit a canvas rectangle on screen
+    ///
+    /// Only applies while a desktop is shown and no transition, drag or
+    /// fullscreen window has the camera.
+    pub fn zoom_to_rect(&mut self, rect: Rect, now_ms: f64) {
+        if !self.can_animate_camera() {
+            debug!("zoom_to_rect blocked");
+            return;
+        }
+        if !(rect.x.is_finite() && rect.y.is_finite() && rect.width > 0.0 && rect.height > 0.0) {
+            return;
+        }
+
+        let target = Camera::fit_rect(
+            rect,
+            self.viewport.screen_size,
+            FIT_PADDING,
+            FIT_MIN_ZOOM,
+            FIT_MAX_ZOOM,
+        );
+        self.start_camera_animation(
+            CameraAnimation::new(self.viewport.to_camera(), target, now_ms),
+            now_ms,
+        );
+        self.last_activity_ms = now_ms;
+    }
+
+    /// Whether a zoom-to-fit animation may take over the camera
+    fn can_animate_camera(&self) -> bool {
+        self.view_mode.is_desktop()
+            && !self.is_crossfading()
+            && !self.input.is_dragging()
+            && !self.is_fullscreen_active()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{Size, Vec2};
+    use crate::transition::CAMERA_ANIMATION_DURATION_MS;
+    use crate::window::WindowConfig;
+
+    fn engine() -> DesktopEngine {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        engine
+    }
+
+    fn add_window(engine: &mut DesktopEngine, x: f32, y: f32) -> WindowId {
+        engine.create_window(WindowConfig {
+            position: Some(Vec2::new(x, y)),
+            size: Size::new(800.0, 600.0),
+            ..Default::default()
+        })
+    }
+
+    fn finish_animation(engine: &mut DesktopEngine) {
+        engine.tick_transition(CAMERA_ANIMATION_DURATION_MS as f64 + 1.0);
+    }
+
+    #[test]
+    fn test_zoom_to_window_centers_at_full_zoom() {
+        let mut engine = engine();
+        let id = add_window(&mut engine, 5000.0, 5000.0);
+        engine.viewport.zoom = 0.2;
+
+        engine.zoom_to_window(id, 0.0);
+        finish_animation(&mut engine);
+
+        assert_eq!(engine.viewport.center, Vec2::new(5400.0, 5300.0));
+        assert_eq!(engine.viewport.zoom, 1.0);
+    }
+
+    #[test]
+    fn test_zoom_to_fit_all_shows_every_window() {
+        let mut engine = engine();
+        add_window(&mut engine, -3000.0, 0.0);
+        add_window(&mut engine, 3000.0, 0.0);
+
+        engine.zoom_to_fit_all(0.0);
+        finish_animation(&mut engine);
+
+        let visible = engine.viewport.visible_rect();
+        assert!(visible.x <= -3000.0 && visible.right() >= 3800.0);
+        assert!(engine.viewport.zoom < 1.0);
+    }
+
+    #[test]
+    fn test_zoom_to_rect_ignores_empty_rect() {
+        let mut engine = engine();
+        engine.zoom_to_rect(Rect::new(0.0, 0.0, 0.0, 100.0), 0.0);
+        assert!(engine.camera_animation.is_none());
+    }
 }
@@ -0,0 +1,230 @@
+//! Pen annotations on the active desktop
+//!
+//! While an [`AnnotationTool`] is selected the frontend sends pen (or mouse)
+//! input here instead of `handle_pointer_*`. Points arrive in screen
+//! coordinates and are stored in desktop-local canvas coordinates.
+//!
+//! The frontend redraws the layer when `annotation_revision` changes (or the
+//! active desktop does); panning and zooming only change the transform.
+
+use super::DesktopEngine;
+use crate::annotation::{AnnotationTool, Stroke, StrokeId, StrokePoint};
+use crate::error::DesktopResult;
+use crate::math::Vec2;
+use tracing::debug;
+
+/// Annotation gesture in progress
+#[derive(Clone, Debug)]
+pub(crate) enum AnnotationGesture {
+    Drawing(Stroke),
+    Erasing { radius: f32 },
+}
+
+impl DesktopEngine {
+    /// Select an annotation tool, or `None` to stop annotating
+    ///
+    /// A stroke in progress is discarded.
+    pub fn set_annotation_tool(&mut self, tool: Option<AnnotationTool>) {
+        if self.annotation_gesture.take().is_some() {
+            self.annotation_revision += 1;
+        }
+        self.annotation_tool = tool;
+    }
+
+    /// Currently selected annotation tool
+    #[inline]
+    pub fn annotation_tool(&self) -> Option<&AnnotationTool> {
+        self.annotation_tool.as_ref()
+    }
+
+    /// Start a stroke (or erasing) at a screen point
+    ///
+    /// Returns false if no tool is selected or the canvas can't be drawn on
+    /// right now (void, transition, window drag).
+    pub fn begin_stroke(&mut self, screen_x: f32, screen_y: f32, pressure: f32) -> bool {
+        let Some(tool) = &self.annotation_tool else {
+            return false;
+        };
+        if !self.view_mode.is_desktop() || self.is_crossfading() || self.input.is_dragging() {
+            return false;
+        }
+
+        self.annotation_gesture = Some(match tool {
+            AnnotationTool::Pen { color, width } => {
+                AnnotationGesture::Drawing(Stroke::new(color, *width))
+            }
+            AnnotationTool::Eraser { radius } => AnnotationGesture::Erasing { radius: *radius },
+        });
+        self.extend_stroke(screen_x, screen_y, pressure);
+        true
+    }
+
+    /// Continue the gesture started by `begin_stroke`
+    pub fn extend_stroke(&mut self, screen_x: f32, screen_y: f32, pressure: f32) {
+        let point = self
+            .viewport
+            .screen_to_canvas(Vec2::new(screen_x, screen_y));
+        let changed = match &mut self.annotation_gesture {
+            Some(AnnotationGesture::Drawing(stroke)) => {
+                stroke.push(StrokePoint::new(point, pressure))
+            }
+            Some(AnnotationGesture::Erasing { radius }) => {
+                let radius = *radius;
+                self.desktops
+                    .active_desktop_mut()
+                    .annotations
+                    .erase_at(point, radius)
+                    > 0
+            }
+            None => false,
+        };
+        if changed {
+            self.annotation_revision += 1;
+        }
+    }
+
+    /// Finish the gesture, adding a drawn stroke to the active desktop
+    ///
+    /// Returns the new stroke's ID.
+    pub fn end_stroke(&mut self) -> Option<StrokeId> {
+        let AnnotationGesture::Drawing(stroke) = self.annotation_gesture.take()? else {
+            return None;
+        };
+        if stroke.points.is_empty() {
+            return None;
+        }
+
+        let id = self.desktops.active_desktop_mut().annotations.add(stroke);
+        self.annotation_revision += 1;
+        debug!(stroke_id = id, "annotation stroke added");
+        Some(id)
+    }
+
+    /// Remove the most recent stroke on the active desktop
+    pub fn undo_stroke(&mut self) -> bool {
+        let removed = self.desktops.active_desktop_mut().annotations.pop();
+        if removed.is_some() {
+            self.annotation_revision += 1;
+        }
+        removed.is_some()
+    }
+
+    /// Remove all strokes from a desktop
+    pub fn clear_annotations(&mut self, index: usize) -> DesktopResult<()> {
+        self.check_desktop_index(index)?;
+        let id = self.desktops.desktops()[index].id;
+        if let Some(desktop) = self.desktops.get_mut(id) {
+            desktop.annotations.clear();
+        }
+        self.annotation_revision += 1;
+        Ok(())
+    }
+
+    /// Strokes on the active desktop, back to front, including the one
+    /// being drawn
+    pub fn annotations(&self) -> Vec<&Stroke> {
+        let mut strokes: Vec<&Stroke> = self
+            .desktops
+            .active_desktop()
+            .annotations
+            .strokes()
+            .iter()
+            .collect();
+        if let Some(AnnotationGesture::Drawing(stroke)) = &self.annotation_gesture {
+            strokes.push(stroke);
+        }
+        strokes
+    }
+
+    /// Counter bumped whenever annotations change
+    #[inline]
+    pub fn annotation_revision(&self) -> u64 {
+        self.annotation_revision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine_with_pen() -> DesktopEngine {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        engine.set_annotation_tool(Some(AnnotationTool::Pen {
+            color: "#ff0".to_string(),
+            width: 3.0,
+        }));
+        engine
+    }
+
+    #[test]
+    fn test_stroke_stored_in_canvas_coordinates() {
+        let mut engine = engine_with_pen();
+        engine.viewport.zoom = 2.0;
+        let start = engine.viewport.screen_to_canvas(Vec2::new(100.0, 100.0));
+
+        assert!(engine.begin_stroke(100.0, 100.0, 0.8));
+        engine.extend_stroke(200.0, 100.0, 0.6);
+        // The in-progress stroke is exported too
+        assert_eq!(engine.annotations().len(), 1);
+        let id = engine.end_stroke().unwrap();
+
+        let strokes = engine.annotations();
+        assert_eq!(strokes[0].id, id);
+        assert_eq!(strokes[0].points[0].position, start);
+        // 100 screen px at 2x zoom is 50 canvas units
+        let end = strokes[0].points[1].position;
+        assert!((end.x - start.x - 50.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_eraser_and_undo() {
+        let mut engine = engine_with_pen();
+        engine.begin_stroke(100.0, 100.0, 0.5);
+        engine.extend_stroke(300.0, 100.0, 0.5);
+        engine.end_stroke();
+        engine.begin_stroke(100.0, 500.0, 0.5);
+        engine.extend_stroke(300.0, 500.0, 0.5);
+        engine.end_stroke();
+
+        engine.set_annotation_tool(Some(AnnotationTool::Eraser { radius: 10.0 }));
+        let revision = engine.annotation_revision();
+        engine.begin_stroke(200.0, 105.0, 0.5);
+        engine.end_stroke();
+        assert_eq!(engine.annotations().len(), 1);
+        assert!(engine.annotation_revision() > revision);
+
+        assert!(engine.undo_stroke());
+        assert!(engine.annotations().is_empty());
+    }
+
+    #[test]
+    fn test_no_drawing_without_tool_or_in_void() {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        assert!(!engine.begin_stroke(0.0, 0.0, 0.5));
+
+        let mut engine = engine_with_pen();
+        engine.enter_void(0.0);
+        engine.tick_transition(10_000.0);
+        assert!(!engine.begin_stroke(0.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_annotations_persist_per_desktop() {
+        let mut engine = engine_with_pen();
+        engine.begin_stroke(10.0, 10.0, 0.5);
+        engine.extend_stroke(60.0, 10.0, 0.5);
+        engine.end_stroke();
+        engine.create_desktop("Empty");
+
+        let snapshot = engine.export_snapshot();
+        assert_eq!(snapshot.desktops[0].annotations.strokes().len(), 1);
+        assert!(snapshot.desktops[1].annotations.is_empty());
+
+        let mut restored = DesktopEngine::new();
+        restored.init(1920.0, 1080.0);
+        restored.import_snapshot(snapshot).unwrap();
+        assert_eq!(restored.annotations().len(), 1);
+    }
+}
@@ -0,0 +1,479 @@
+//! Desktop tile operations
+//!
+//! Rename, reorder, delete, duplicate and create-from-template, as offered
+//! on desktop tiles in the void, plus the VFS folders bound to a desktop.
+//! Windows use desktop-local coordinates, so reordering only moves a
+//! desktop's tile; its windows and camera are untouched.
+//!
+//! Structural changes are refused while a crossfade or drag is in progress,
+//! because both refer to desktops by index.
+
+use super::DesktopEngine;
+use crate::desktop::{DesktopTemplate, FolderBinding, TemplateInstance, TemplateWindow, ViewMode};
+use crate::error::{DesktopError, DesktopResult};
+use crate::persistence::Snapshot;
+use crate::window::{WindowId, WindowState};
+use tracing::info;
+
+impl DesktopEngine {
+    /// Rename a desktop
+    pub fn rename_desktop(&mut self, index: usize, name: &str) -> DesktopResult<()> {
+        self.check_desktop_index(index)?;
+        self.desktops.rename(index, name);
+        Ok(())
+    }
+
+    /// Move a desktop to a new position in the void
+    pub fn move_desktop(&mut self, from: usize, to: usize) -> DesktopResult<()> {
+        self.check_can_restructure("move_desktop")?;
+        self.check_desktop_index(from)?;
+        self.check_desktop_index(to)?;
+
+        self.desktops.move_desktop(from, to);
+        self.sync_view_mode_index();
+        info!(from, to, "desktop moved");
+        Ok(())
+    }
+
+    /// Delete a desktop, moving its windows to another desktop
+    ///
+    /// `rehome_to` is the index (before deletion) of the desktop that
+    /// receives the windows.
+    pub fn delete_desktop(&mut self, index: usize, rehome_to: usize) -> DesktopResult<()> {
+        self.check_can_restructure("delete_desktop")?;
+        self.check_desktop_index(index)?;
+        self.check_desktop_index(rehome_to)?;
+        if self.desktops.count() <= 1 {
+            return Err(DesktopError::InvalidOperation {
+                op: "delete_desktop",
+                reason: "cannot delete the last desktop",
+            });
+        }
+        if index == rehome_to {
+            return Err(DesktopError::InvalidOperation {
+                op: "delete_desktop",
+                reason: "windows must move to a different desktop",
+            });
+        }
+
+        let windows: Vec<WindowId> = self.desktops.desktops()[index].windows.clone();
+        for &id in &windows {
+            self.desktops.move_window_to(id, rehome_to);
+        }
+        self.desktops.move_widgets(index, rehome_to);
+
+        let was_active = self.desktops.active_index() == index;
+        self.desktops.delete(index);
+        self.sync_view_mode_index();
+        if was_active {
+            self.focus_top_window_on_desktop(self.desktops.active_index());
+        }
+
+        info!(index, rehome_to, windows = windows.len(), "desktop deleted");
+        Ok(())
+    }
+
+    /// Move a window to another desktop
+    ///
+    /// The window keeps its desktop-local position and goes on top of the
+    /// target desktop. It takes focus if the target is the active desktop;
+    /// otherwise focus passes to the top window left on the active desktop.
+    pub fn move_window_to_desktop(
+        &mut self,
+        window_id: WindowId,
+        desktop_index: usize,
+    ) -> DesktopResult<()> {
+        self.check_can_restructure("move_window_to_desktop")?;
+        self.check_desktop_index(desktop_index)?;
+        if self.windows.get(window_id).is_none() {
+            return Err(DesktopError::WindowNotFound(window_id));
+        }
+        if self.desktops.desktops()[desktop_index].contains_window(window_id) {
+            return Ok(());
+        }
+
+        // Fullscreen geometry belongs to the source desktop's screen
+        self.exit_fullscreen(window_id);
+        self.desktops.move_window_to(window_id, desktop_index);
+
+        let active = self.desktops.active_index();
+        if desktop_index == active && self.view_mode.is_desktop() {
+            self.focus_window(window_id);
+        } else {
+            self.windows.raise(window_id);
+            self.focus_top_window_on_desktop(active);
+        }
+
+        info!(window_id, desktop_index, "window moved to desktop");
+        Ok(())
+    }
+
+    /// Capture a desktop's layout as a template
+    pub fn desktop_template(&self, index: usize) -> DesktopResult<DesktopTemplate> {
+        self.check_desktop_index(index)?;
+        let desktop = &self.desktops.desktops()[index];
+        let windows = self
+            .windows
+            .windows_by_z()
+            .into_iter()
+            .filter(|w| desktop.contains_window(w.id) && w.state != WindowState::Minimized)
+            .map(|w| {
+                // Save the normal geometry of maximized/fullscreen windows
+                let (position, size) = w
+                    .fullscreen_restore
+                    .map(|(_, pos, size)| (pos, size))
+                    .or(w.restore_rect)
+                    .unwrap_or((w.position, w.size));
+                TemplateWindow {
+                    app_id: w.app_id.clone(),
+                    title: w.title.clone(),
+                    position,
+                    size,
+                }
+            })
+            .collect();
+
+        Ok(DesktopTemplate {
+            name: desktop.name.clone(),
+            background: Some(desktop.background.clone()),
+            camera: Some(desktop.camera),
+            windows,
+            folders: desktop.folders.clone(),
+        })
+    }
+
+    /// Create a desktop laid out from a template
+    ///
+    /// Windows are opened for each template window's app; window rules are
+    /// not applied, since the template's layout is explicit. Processes are
+    /// not spawned here: the caller starts the apps that need one and links
+    /// them to the returned windows with `set_window_process_id`.
+    pub fn create_desktop_from_template(
+        &mut self,
+        template: &DesktopTemplate,
+    ) -> DesktopResult<TemplateInstance> {
+        self.check_can_restructure("create_desktop_from_template")?;
+        FolderBinding::validate_all(&template.folders)?;
+
+        self.create_desktop(&template.name);
+        let index = self.desktops.count() - 1;
+        if let Some(background) = &template.background {
+            self.desktops.set_desktop_background(index, background);
+        }
+        if let Some(camera) = template.camera {
+            self.desktops
+                .save_desktop_camera(index, camera.center, camera.zoom);
+        }
+        self.desktops
+            .set_desktop_folders(index, template.folders.clone());
+
+        let mut windows = Vec::with_capacity(template.windows.len());
+        for window in &template.windows {
+            let mut config = self.app_window_config(&window.app_id);
+            if !window.title.is_empty() {
+                config.title = window.title.clone();
+            }
+            config.position = Some(window.position);
+            config.size = window.size;
+            let id = self.insert_window(index, config);
+            windows.push((id, window.app_id.clone()));
+        }
+
+        info!(
+            index,
+            name = %template.name,
+            windows = windows.len(),
+            folders = template.folders.len(),
+            "desktop created from template"
+        );
+        Ok(TemplateInstance {
+            desktop: index,
+            windows,
+        })
+    }
+
+    /// Create a copy of a desktop's layout next to the original
+    ///
+    /// Apps are opened fresh; window contents are not copied.
+    pub fn duplicate_desktop(&mut self, index: usize) -> DesktopResult<usize> {
+        let mut template = self.desktop_template(index)?;
+        template.name = format!("{} copy", template.name);
+
+        let copy = self.create_desktop_from_template(&template)?.desktop;
+        self.desktops.move_desktop(copy, index + 1);
+        self.sync_view_mode_index();
+        Ok(index + 1)
+    }
+
+    /// Bind named VFS folders to a desktop, replacing its current bindings
+    ///
+    /// Names must be non-empty and unique, and paths absolute.
+    pub fn set_desktop_folders(
+        &mut self,
+        index: usize,
+        folders: Vec<FolderBinding>,
+    ) -> DesktopResult<()> {
+        self.check_desktop_index(index)?;
+        FolderBinding::validate_all(&folders)?;
+        self.desktops.set_desktop_folders(index, folders);
+        Ok(())
+    }
+
+    /// Folders bound to a desktop
+    pub fn desktop_folders(&self, index: usize) -> DesktopResult<&[FolderBinding]> {
+        self.check_desktop_index(index)?;
+        Ok(&self.desktops.desktops()[index].folders)
+    }
+
+    /// Snapshot of the desktop list for persistence
+    pub fn export_snapshot(&self) -> Snapshot {
+        let mut desktops = self.desktops.export_for_persistence();
+        // The shown desktop's camera lives in the viewport until it is left
+        if let ViewMode::Desktop { index } = self.view_mode {
+            if let Some(persisted) = desktops.get_mut(index) {
+                persisted.camera = self.viewport.to_camera();
+            }
+        }
+        Snapshot::new(self.desktops.active_index(), desktops)
+    }
+
+    /// Restore the desktop list (order, names, cameras, backgrounds)
+    pub fn import_snapshot(&mut self, mut snapshot: Snapshot) -> DesktopResult<()> {
+        self.check_can_restructure("import_snapshot")?;
+        if snapshot.version > Snapshot::CURRENT_VERSION {
+            return Err(DesktopError::PersistenceError(format!(
+                "snapshot version {} is newer than supported version {}",
+                snapshot.version,
+                Snapshot::CURRENT_VERSION
+            )));
+        }
+        if snapshot.needs_migration() {
+            snapshot.migrate();
+        }
+
+        self.desktops.restore_from_persistence(&snapshot.desktops);
+        self.annotation_revision += 1;
+        let max_widget_id = self
+            .desktops
+            .desktops()
+            .iter()
+            .filter_map(|d| d.widgets.max_id())
+            .max()
+            .unwrap_or(0);
+        self.next_widget_id = self.next_widget_id.max(max_widget_id);
+        let active = snapshot
+            .active_desktop
+            .min(self.desktops.count().saturating_sub(1));
+        self.desktops.switch_to(active);
+        if self.view_mode.is_desktop() {
+            self.view_mode = ViewMode::Desktop { index: active };
+            let camera = self.desktops.get_active_camera();
+            self.viewport.center = camera.center;
+            self.viewport.zoom = camera.zoom;
+        }
+
+        info!(
+            desktops = self.desktops.count(),
+            active, "desktops restored"
+        );
+        Ok(())
+    }
+
+    pub(crate) fn check_desktop_index(&self, index: usize) -> DesktopResult<()> {
+        let count = self.desktops.count();
+        if index < count {
+            Ok(())
+        } else {
+            Err(DesktopError::DesktopIndexOutOfBounds { index, count })
+        }
+    }
+
+    fn check_can_restructure(&self, op: &'static str) -> DesktopResult<()> {
+        if self.is_crossfading() || self.input.is_dragging() {
+            return Err(DesktopError::InvalidOperation {
+                op,
+                reason: "a transition or drag is in progress",
+            });
+        }
+        Ok(())
+    }
+
+    /// Keep a desktop view pointing at the active desktop after reordering
+    fn sync_view_mode_index(&mut self) {
+        if self.view_mode.is_desktop() {
+            self.view_mode = ViewMode::Desktop {
+                index: self.desktops.active_index(),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{Size, Vec2};
+    use crate::window::WindowConfig;
+
+    fn engine_with_desktops() -> DesktopEngine {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        engine.create_desktop("Work");
+        engine.create_desktop("Play");
+        engine
+    }
+
+    fn names(engine: &DesktopEngine) -> Vec<String> {
+        engine
+            .desktops
+            .desktops()
+            .iter()
+            .map(|d| d.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_delete_rehomes_windows() {
+        let mut engine = engine_with_desktops();
+        let id = engine.launch_app("terminal");
+
+        assert!(engine.delete_desktop(0, 0).is_err());
+        engine.delete_desktop(0, 2).unwrap();
+
+        assert_eq!(names(&engine), vec!["Work", "Play"]);
+        assert!(engine.desktops.desktops()[1].contains_window(id));
+        assert_eq!(*engine.get_view_mode(), ViewMode::Desktop { index: 0 });
+    }
+
+    #[test]
+    fn test_delete_last_desktop_refused() {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        assert!(matches!(
+            engine.delete_desktop(0, 0),
+            Err(DesktopError::InvalidOperation { .. })
+        ));
+        assert_eq!(
+            engine.delete_desktop(3, 0),
+            Err(DesktopError::DesktopIndexOutOfBounds { index: 3, count: 1 })
+        );
+    }
+
+    #[test]
+    fn test_move_window_to_desktop_fixes_focus() {
+        let mut engine = engine_with_desktops();
+        let stays = engine.launch_app("terminal");
+        let moves = engine.launch_app("browser");
+        assert_eq!(engine.windows.focused(), Some(moves));
+
+        engine.move_window_to_desktop(moves, 1).unwrap();
+        assert!(engine.desktops.desktops()[1].contains_window(moves));
+        assert!(!engine.desktops.desktops()[0].contains_window(moves));
+        assert_eq!(engine.windows.focused(), Some(stays));
+
+        // Moving back onto the active desktop focuses it again
+        engine.move_window_to_desktop(moves, 0).unwrap();
+        assert_eq!(engine.windows.focused(), Some(moves));
+
+        assert_eq!(
+            engine.move_window_to_desktop(999, 1),
+            Err(DesktopError::WindowNotFound(999))
+        );
+    }
+
+    #[test]
+    fn test_move_and_rename() {
+        let mut engine = engine_with_desktops();
+        engine.move_desktop(0, 2).unwrap();
+        engine.rename_desktop(0, "Office").unwrap();
+
+        assert_eq!(names(&engine), vec!["Office", "Play", "Main"]);
+        assert_eq!(*engine.get_view_mode(), ViewMode::Desktop { index: 2 });
+    }
+
+    #[test]
+    fn test_duplicate_copies_layout() {
+        let mut engine = engine_with_desktops();
+        let id = engine.create_window(WindowConfig {
+            title: "Notes".to_string(),
+            app_id: "notes".to_string(),
+            position: Some(Vec2::new(10.0, 20.0)),
+            size: Size::new(500.0, 400.0),
+            ..Default::default()
+        });
+        engine.maximize_window(id);
+
+        let copy = engine.duplicate_desktop(0).unwrap();
+        assert_eq!(copy, 1);
+        assert_eq!(names(&engine), vec!["Main", "Main copy", "Work", "Play"]);
+
+        let windows = &engine.desktops.desktops()[copy].windows;
+        assert_eq!(windows.len(), 1);
+        let window = engine.windows.get(windows[0]).unwrap();
+        assert_eq!(window.title, "Notes");
+        assert_eq!(window.position, Vec2::new(10.0, 20.0));
+        assert_eq!(window.size, Size::new(500.0, 400.0));
+        // The active desktop is unchanged
+        assert_eq!(engine.desktops.active_index(), 0);
+    }
+
+    #[test]
+    fn test_template_round_trip_with_folders() {
+        let mut engine = engine_with_desktops();
+        engine
+            .set_desktop_folders(1, vec![FolderBinding::new("project", "/home/code/zos")])
+            .unwrap();
+        engine.switch_desktop(1, 0.0);
+        engine.tick_transition(10_000.0);
+        engine.launch_app("terminal");
+        engine.launch_app("notes");
+
+        let template = engine.desktop_template(1).unwrap();
+        let instance = engine.create_desktop_from_template(&template).unwrap();
+
+        assert_eq!(instance.desktop, 3);
+        let apps: Vec<&str> = instance
+            .windows
+            .iter()
+            .map(|(_, app)| app.as_str())
+            .collect();
+        assert_eq!(apps, vec!["terminal", "notes"]);
+        let desktop = &engine.desktops.desktops()[3];
+        assert_eq!(desktop.folder("project"), Some("/home/code/zos"));
+        for (id, _) in &instance.windows {
+            assert!(desktop.contains_window(*id));
+        }
+    }
+
+    #[test]
+    fn test_invalid_folders_refused() {
+        let mut engine = engine_with_desktops();
+        let relative = vec![FolderBinding::new("project", "code/zos")];
+        assert!(engine.set_desktop_folders(0, relative.clone()).is_err());
+        assert!(engine.set_desktop_folders(7, Vec::new()).is_err());
+
+        let template = DesktopTemplate {
+            name: "Broken".to_string(),
+            folders: relative,
+            ..Default::default()
+        };
+        assert!(engine.create_desktop_from_template(&template).is_err());
+        assert_eq!(engine.desktops.count(), 3);
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut engine = engine_with_desktops();
+        engine.move_desktop(2, 0).unwrap();
+        engine.set_desktop_background(1, "mist");
+        let snapshot = engine.export_snapshot();
+
+        let mut restored = DesktopEngine::new();
+        restored.init(1920.0, 1080.0);
+        restored.import_snapshot(snapshot).unwrap();
+
+        assert_eq!(names(&restored), vec!["Play", "Main", "Work"]);
+        assert_eq!(restored.desktops.desktops()[1].background, "mist");
+        assert_eq!(restored.desktops.active_index(), 1);
+        assert_eq!(*restored.get_view_mode(), ViewMode::Desktop { index: 1 });
+    }
+}
@@ -0,0 +1,213 @@
+//! Focus requests from apps
+//!
+//! `focus_window` only raises a window. When an app asks for one of its
+//! windows to be shown (a notification click, "open" on an app that is
+//! already running), the window may be minimized, off screen, or on another
+//! desktop. `request_focus` brings it in front of the user, and
+//! [`FocusPolicy`] decides what happens when it lives on another desktop.
+
+use super::DesktopEngine;
+use crate::math::Vec2;
+use crate::window::{WindowId, WindowState};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+/// What a focus request does for a window on another desktop
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusPolicy {
+    /// Crossfade to the window's desktop
+    #[default]
+    SwitchDesktop,
+    /// Move the window to the active desktop, centered in view
+    PullWindow,
+}
+
+impl FocusPolicy {
+    /// Parse a policy name as used in settings
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "switch_desktop" => Some(Self::SwitchDesktop),
+            "pull_window" => Some(Self::PullWindow),
+            _ => None,
+        }
+    }
+
+    /// Policy name as used in settings
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SwitchDesktop => "switch_desktop",
+            Self::PullWindow => "pull_window",
+        }
+    }
+}
+
+impl DesktopEngine {
+    /// Set how focus requests reach windows on other desktops
+    pub fn set_focus_policy(&mut self, policy: FocusPolicy) {
+        self.focus_policy = policy;
+    }
+
+    /// Current focus policy
+    #[inline]
+    pub fn focus_policy(&self) -> FocusPolicy {
+        self.focus_policy
+    }
+
+    /// Bring a window in front of the user on behalf of an app
+    ///
+    /// Restores the window if minimized, focuses it and pans to it. A window
+    /// on another desktop (or any window while the void is shown) is handled
+    /// per the focus policy. Requests made while a drag is in progress are
+    /// ignored so they don't yank the desktop away from under the pointer.
+    pub fn request_focus(&mut self, id: WindowId, now_ms: f64) {
+        let Some(state) = self.windows.get(id).map(|w| w.state) else {
+            return;
+        };
+        let Some(home) = self
+            .desktops
+            .desktops()
+            .iter()
+            .position(|d| d.contains_window(id))
+        else {
+            return;
+        };
+        if self.input.is_dragging() {
+            debug!(window_id = id, "focus request ignored during drag");
+            return;
+        }
+
+        if state == WindowState::Minimized {
+            self.restore_window(id);
+        }
+
+        let active = self.desktops.active_index();
+        if home == active && self.view_mode.is_desktop() {
+            self.focus_window(id);
+            self.pan_to_window(id, now_ms);
+            return;
+        }
+
+        match self.focus_policy {
+            FocusPolicy::SwitchDesktop => self.focus_on_home_desktop(id, home, now_ms),
+            FocusPolicy::PullWindow => self.pull_window_to_active(id, now_ms),
+        }
+        info!(window_id = id, from_desktop = home, policy = ?self.focus_policy, "focus request");
+    }
+
+    /// Show the window's own desktop with the window in view
+    fn focus_on_home_desktop(&mut self, id: WindowId, home: usize, now_ms: f64) {
+        // Frame the window in the camera the desktop will open with
+        if let (Some(camera), Some(rect)) = (
+            self.desktops.get_desktop_camera(home),
+            self.windows.get(id).map(|w| w.rect()),
+        ) {
+            if !camera
+                .visible_rect(self.viewport.screen_size)
+                .intersects(&rect)
+            {
+                self.desktops
+                    .save_desktop_camera(home, rect.center(), camera.zoom);
+            }
+        }
+
+        if self.view_mode.is_void() {
+            self.exit_void(home, now_ms);
+        } else {
+            self.switch_desktop(home, now_ms);
+        }
+        self.focus_window(id);
+    }
+
+    /// Move the window onto the active desktop, centered in the current view
+    fn pull_window_to_active(&mut self, id: WindowId, now_ms: f64) {
+        let active = self.desktops.active_index();
+        if self.move_window_to_desktop(id, active).is_err() {
+            debug!(window_id = id, "focus request could not pull window");
+            return;
+        }
+
+        let view = if self.view_mode.is_desktop() {
+            self.viewport.visible_rect()
+        } else {
+            self.desktops
+                .get_desktop_camera(active)
+                .map(|c| c.visible_rect(self.viewport.screen_size))
+                .unwrap_or_else(|| self.viewport.visible_rect())
+        };
+        if let Some(window) = self.windows.get(id) {
+            let center = view.center();
+            let position = Vec2::new(
+                center.x - window.size.width / 2.0,
+                center.y - window.size.height / 2.0,
+            );
+            self.windows.move_window(id, position);
+        }
+
+        if self.view_mode.is_void() {
+            self.exit_void(active, now_ms);
+        }
+        self.focus_window(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::desktop::ViewMode;
+    use crate::math::Size;
+    use crate::window::WindowConfig;
+
+    /// Engine with a window on a second desktop, back on the first
+    fn engine_with_remote_window() -> (DesktopEngine, WindowId) {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        engine.create_desktop("Second");
+        let id = engine.create_window(WindowConfig {
+            position: Some(Vec2::new(5000.0, 5000.0)),
+            size: Size::new(800.0, 600.0),
+            ..Default::default()
+        });
+        engine.move_window_to_desktop(id, 1).unwrap();
+        (engine, id)
+    }
+
+    #[test]
+    fn test_policy_names_round_trip() {
+        for policy in [FocusPolicy::SwitchDesktop, FocusPolicy::PullWindow] {
+            assert_eq!(FocusPolicy::from_name(policy.name()), Some(policy));
+        }
+        assert_eq!(FocusPolicy::from_name("teleport"), None);
+    }
+
+    #[test]
+    fn test_switch_policy_switches_desktop() {
+        let (mut engine, id) = engine_with_remote_window();
+        engine.request_focus(id, 0.0);
+        engine.tick_transition(10_000.0);
+
+        assert_eq!(*engine.get_view_mode(), ViewMode::Desktop { index: 1 });
+        assert_eq!(engine.windows.focused(), Some(id));
+        // The desktop opens with the window in view
+        let rect = engine.windows.get(id).unwrap().rect();
+        assert!(engine.viewport.visible_rect().intersects(&rect));
+    }
+
+    #[test]
+    fn test_pull_policy_moves_window_into_view() {
+        let (mut engine, id) = engine_with_remote_window();
+        engine.set_focus_policy(FocusPolicy::PullWindow);
+        engine.minimize_window(id);
+        engine.request_focus(id, 0.0);
+
+        assert_eq!(engine.desktops.active_index(), 0);
+        assert!(engine.desktops.desktops()[0].contains_window(id));
+        let window = engine.windows.get(id).unwrap();
+        assert_eq!(window.state, WindowState::Normal);
+        assert_eq!(
+            window.rect().center(),
+            engine.viewport.visible_rect().center()
+        );
+        assert_eq!(engine.windows.focused(), Some(id));
+    }
+}
@@ -0,0 +1,213 @@
+//! Fullscreen windows
+//!
+//! A fullscreen window covers the whole screen of its desktop: the frame is
+//! not drawn, the taskbar is hidden, and the camera is locked so pan/zoom
+//! input reaches the app instead. Only one window per desktop can be
+//! fullscreen. Entering the void leaves fullscreen on every desktop, since
+//! the void shows desktops side by side.
+
+use super::DesktopEngine;
+use crate::desktop::ViewMode;
+use crate::math::Rect;
+use crate::window::{WindowId, WindowState};
+use tracing::{debug, info};
+
+impl DesktopEngine {
+    /// Make a window fullscreen on the active desktop
+    pub fn enter_fullscreen(&mut self, id: WindowId) {
+        if !self.can_enter_fullscreen(id) {
+            debug!(window_id = id, "enter_fullscreen blocked");
+            return;
+        }
+
+        // Only one fullscreen window per desktop
+        if let Some(current) = self.fullscreen_window() {
+            if current != id {
+                self.windows.exit_fullscreen(current);
+            }
+        }
+
+        self.camera_animation = None;
+        let bounds = self.viewport.visible_rect();
+        self.windows.enter_fullscreen(id, bounds);
+        self.focus_window(id);
+        info!(window_id = id, "window entered fullscreen");
+    }
+
+    /// Return a fullscreen window to its previous state
+    pub fn exit_fullscreen(&mut self, id: WindowId) {
+        if self.windows.exit_fullscreen(id) {
+            info!(window_id = id, "window left fullscreen");
+        }
+    }
+
+    /// Enter or leave fullscreen
+    pub fn toggle_fullscreen(&mut self, id: WindowId) {
+        match self.windows.get(id).map(|w| w.state) {
+            Some(WindowState::Fullscreen) => self.exit_fullscreen(id),
+            Some(_) => self.enter_fullscreen(id),
+            None => {}
+        }
+    }
+
+    /// The fullscreen window currently covering the screen, if any
+    ///
+    /// Only a visible (not minimized) window on the active desktop counts,
+    /// and only while a desktop (not the void) is shown.
+    pub fn fullscreen_window(&self) -> Option<WindowId> {
+        if !self.view_mode.is_desktop() {
+            return None;
+        }
+        let desktop = self.desktops.active_desktop();
+        self.windows
+            .windows_by_z()
+            .into_iter()
+            .rev()
+            .find(|w| w.is_fullscreen() && desktop.contains_window(w.id))
+            .map(|w| w.id)
+    }
+
+    /// Whether the taskbar should be hidden for a fullscreen window
+    #[inline]
+    pub fn is_fullscreen_active(&self) -> bool {
+        self.fullscreen_window().is_some()
+    }
+
+    /// Leave fullscreen on every desktop
+    pub(crate) fn exit_all_fullscreen(&mut self) {
+        let ids: Vec<WindowId> = self
+            .windows
+            .all_windows()
+            .filter(|w| {
+                w.is_fullscreen()
+                    || (w.state == WindowState::Minimized
+                        && w.prev_state == Some(WindowState::Fullscreen))
+            })
+            .map(|w| w.id)
+            .collect();
+        for id in ids {
+            self.exit_fullscreen(id);
+        }
+    }
+
+    /// Re-fit fullscreen windows to the screen (after a resize or restore)
+    pub(crate) fn refit_fullscreen_windows(&mut self) {
+        let fits: Vec<(WindowId, Rect)> = self
+            .windows
+            .all_windows()
+            .filter(|w| w.is_fullscreen())
+            .filter_map(|w| {
+                let index = self
+                    .desktops
+                    .desktops()
+                    .iter()
+                    .position(|d| d.contains_window(w.id))?;
+                Some((w.id, self.desktop_screen_rect(index)))
+            })
+            .collect();
+        for (id, bounds) in fits {
+            self.windows.enter_fullscreen(id, bounds);
+        }
+    }
+
+    /// Canvas area covered by the screen when a desktop is shown
+    fn desktop_screen_rect(&self, index: usize) -> Rect {
+        match self.view_mode {
+            ViewMode::Desktop { index: shown } if shown == index => self.viewport.visible_rect(),
+            _ => self
+                .desktops
+                .get_desktop_camera(index)
+                .map(|camera| camera.visible_rect(self.viewport.screen_size))
+                .unwrap_or_else(|| self.viewport.visible_rect()),
+        }
+    }
+
+    /// Fullscreen needs a settled desktop view and a window on it
+    fn can_enter_fullscreen(&self, id: WindowId) -> bool {
+        self.view_mode.is_desktop()
+            && !self.is_crossfading()
+            && !self.input.is_dragging()
+            && self.desktops.active_desktop().contains_window(id)
+            && self
+                .windows
+                .get(id)
+                .is_some_and(|w| w.state != WindowState::Minimized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec2;
+    use crate::window::WindowConfig;
+
+    fn engine_with_window() -> (DesktopEngine, WindowId) {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        let id = engine.create_window(WindowConfig {
+            position: Some(Vec2::new(100.0, 100.0)),
+            size: crate::math::Size::new(800.0, 600.0),
+            ..Default::default()
+        });
+        (engine, id)
+    }
+
+    #[test]
+    fn test_fullscreen_covers_screen_and_locks_camera() {
+        let (mut engine, id) = engine_with_window();
+        engine.enter_fullscreen(id);
+
+        assert_eq!(engine.fullscreen_window(), Some(id));
+        assert_eq!(
+            engine.windows.get(id).unwrap().rect(),
+            engine.viewport.visible_rect()
+        );
+
+        let center = engine.viewport.center;
+        engine.pan(100.0, 100.0);
+        engine.zoom_at(2.0, 10.0, 10.0);
+        assert_eq!(engine.viewport.center, center);
+
+        engine.toggle_fullscreen(id);
+        assert_eq!(engine.fullscreen_window(), None);
+        assert_eq!(
+            engine.windows.get(id).unwrap().rect(),
+            Rect::new(100.0, 100.0, 800.0, 600.0)
+        );
+    }
+
+    #[test]
+    fn test_one_fullscreen_window_per_desktop() {
+        let (mut engine, first) = engine_with_window();
+        let second = engine.launch_app("browser");
+        engine.enter_fullscreen(first);
+        engine.enter_fullscreen(second);
+
+        assert_eq!(engine.fullscreen_window(), Some(second));
+        assert_eq!(
+            engine.windows.get(first).unwrap().state,
+            WindowState::Normal
+        );
+    }
+
+    #[test]
+    fn test_entering_void_exits_fullscreen() {
+        let (mut engine, id) = engine_with_window();
+        engine.enter_fullscreen(id);
+        engine.enter_void(0.0);
+
+        assert_eq!(engine.windows.get(id).unwrap().state, WindowState::Normal);
+        assert!(!engine.is_fullscreen_active());
+    }
+
+    #[test]
+    fn test_screen_resize_refits_fullscreen() {
+        let (mut engine, id) = engine_with_window();
+        engine.enter_fullscreen(id);
+        engine.resize(1280.0, 720.0);
+
+        let rect = engine.windows.get(id).unwrap().rect();
+        assert_eq!(rect, engine.viewport.visible_rect());
+        assert_eq!(rect.width, 1280.0);
+    }
+}
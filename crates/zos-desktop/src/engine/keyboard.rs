@@ -0,0 +1,315 @@
+//! Keyboard commands
+//!
+//! `handle_key` looks a chord up in the hotkey registry and runs the
+//! command. While a window is fullscreen only fullscreen and void commands
+//! apply; every other key goes to the app.
+
+use super::DesktopEngine;
+use crate::keyboard::{
+    CheatSheetEntry, Direction, HotkeyRegistry, KeyChord, KeyCommand, KeyResult, MOVE_STEP,
+    RESIZE_STEP,
+};
+use crate::math::{Size, Vec2};
+use crate::window::{WindowId, WindowState};
+use tracing::debug;
+
+/// Weight of sideways offset against distance along the direction when
+/// picking the next window
+const SIDEWAYS_WEIGHT: f32 = 2.0;
+
+impl DesktopEngine {
+    /// Handle a key press
+    pub fn handle_key(&mut self, keys: &KeyChord, now_ms: f64) -> KeyResult {
+        match self.hotkeys.lookup(keys) {
+            Some(command) => self.run_key_command(command, now_ms),
+            None => KeyResult::Unhandled,
+        }
+    }
+
+    /// Run a keyboard command
+    pub fn run_key_command(&mut self, command: KeyCommand, now_ms: f64) -> KeyResult {
+        if self.is_fullscreen_active() && !command.allowed_in_fullscreen() {
+            return KeyResult::Unhandled;
+        }
+        if command.is_frontend() {
+            return KeyResult::Frontend(command);
+        }
+        debug!(?command, "key command");
+
+        let handled = match command {
+            KeyCommand::Focus { direction } => self.focus_in_direction(direction, now_ms).is_some(),
+            KeyCommand::MoveWindow { direction } => self.nudge_focused_window(direction),
+            KeyCommand::ResizeWindow { direction } => self.resize_focused_window(direction),
+            KeyCommand::PreviousDesktop => self.step_desktop(false, now_ms),
+            KeyCommand::NextDesktop => self.step_desktop(true, now_ms),
+            KeyCommand::ToggleVoid => {
+                if self.view_mode.is_void() {
+                    self.exit_void(self.desktops.active_index(), now_ms);
+                } else {
+                    self.enter_void(now_ms);
+                }
+                true
+            }
+            KeyCommand::ZoomToFit => {
+                self.zoom_to_fit_all(now_ms);
+                true
+            }
+            KeyCommand::ZoomToFocused => match self.windows.focused() {
+                Some(id) => {
+                    self.zoom_to_window(id, now_ms);
+                    true
+                }
+                None => false,
+            },
+            KeyCommand::ToggleFullscreen => match self.windows.focused() {
+                Some(id) => {
+                    self.toggle_fullscreen(id);
+                    true
+                }
+                None => false,
+            },
+            KeyCommand::ExitFullscreen => match self.fullscreen_window() {
+                Some(id) => {
+                    self.exit_fullscreen(id);
+                    true
+                }
+                None => false,
+            },
+            KeyCommand::LaunchTerminal
+            | KeyCommand::CloseWindow
+            | KeyCommand::OpenCommandPalette => false,
+        };
+
+        if handled {
+            KeyResult::Handled
+        } else {
+            KeyResult::Unhandled
+        }
+    }
+
+    /// Hotkey bindings
+    #[inline]
+    pub fn hotkeys(&self) -> &HotkeyRegistry {
+        &self.hotkeys
+    }
+
+    /// Mutable hotkey bindings (to rebind keys)
+    #[inline]
+    pub fn hotkeys_mut(&mut self) -> &mut HotkeyRegistry {
+        &mut self.hotkeys
+    }
+
+    /// Current bindings as a cheat sheet
+    pub fn cheat_sheet(&self) -> Vec<CheatSheetEntry> {
+        self.hotkeys.cheat_sheet()
+    }
+
+    /// Focus and pan to the nearest visible window in a direction
+    ///
+    /// Distances are measured between window centers, from the focused
+    /// window (or the middle of the screen when nothing is focused).
+    /// Windows far off to the side lose to ones straight ahead.
+    pub fn focus_in_direction(&mut self, direction: Direction, now_ms: f64) -> Option<WindowId> {
+        if !self.view_mode.is_desktop() {
+            return None;
+        }
+        let desktop = self.desktops.active_desktop();
+        let focused = self.windows.focused();
+        let origin = focused
+            .and_then(|id| self.windows.get(id))
+            .filter(|w| desktop.contains_window(w.id))
+            .map_or(self.active_camera().center, |w| w.rect().center());
+        let unit = direction.unit();
+
+        let score = |center: Vec2| {
+            let offset = center - origin;
+            let along = offset.x * unit.x + offset.y * unit.y;
+            let sideways = (offset.x * unit.y - offset.y * unit.x).abs();
+            (along > 0.0).then_some(along + sideways * SIDEWAYS_WEIGHT)
+        };
+        let target = self
+            .windows
+            .all_windows()
+            .filter(|w| {
+                Some(w.id) != focused
+                    && desktop.contains_window(w.id)
+                    && w.state != WindowState::Minimized
+            })
+            .filter_map(|w| score(w.rect().center()).map(|s| (w.id, s)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)?;
+
+        self.focus_window(target);
+        self.pan_to_window(target, now_ms);
+        Some(target)
+    }
+
+    /// Move the focused window one step (normal windows only)
+    fn nudge_focused_window(&mut self, direction: Direction) -> bool {
+        let Some(window) = self.focused_normal_window() else {
+            return false;
+        };
+        let (id, position) = (window.id, window.position + direction.unit() * MOVE_STEP);
+        self.move_window(id, position.x, position.y);
+        true
+    }
+
+    /// Resize the focused window one step from its bottom-right corner
+    fn resize_focused_window(&mut self, direction: Direction) -> bool {
+        let Some(window) = self.focused_normal_window() else {
+            return false;
+        };
+        let delta = direction.unit() * RESIZE_STEP;
+        let (id, size) = (
+            window.id,
+            Size::new(window.size.width + delta.x, window.size.height + delta.y),
+        );
+        self.resize_window(id, size.width, size.height);
+        true
+    }
+
+    fn focused_normal_window(&self) -> Option<&crate::window::Window> {
+        if !self.view_mode.is_desktop() {
+            return None;
+        }
+        let desktop = self.desktops.active_desktop();
+        self.windows
+            .focused()
+            .and_then(|id| self.windows.get(id))
+            .filter(|w| w.state == WindowState::Normal && desktop.contains_window(w.id))
+    }
+
+    /// Switch to the next or previous desktop, wrapping around
+    ///
+    /// From the void this leaves the void into that desktop.
+    fn step_desktop(&mut self, forward: bool, now_ms: f64) -> bool {
+        let count = self.desktops.desktops().len();
+        if count <= 1 {
+            return false;
+        }
+        let current = self.desktops.active_index();
+        let next = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+        if self.view_mode.is_void() {
+            self.exit_void(next, now_ms);
+        } else {
+            self.switch_desktop(next, now_ms);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::WindowConfig;
+
+    fn engine() -> DesktopEngine {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        engine
+    }
+
+    fn window_at(engine: &mut DesktopEngine, x: f32, y: f32) -> WindowId {
+        engine.create_window(WindowConfig {
+            position: Some(Vec2::new(x, y)),
+            size: Size::new(300.0, 200.0),
+            ..Default::default()
+        })
+    }
+
+    fn press(engine: &mut DesktopEngine, chord: &str) -> KeyResult {
+        engine.handle_key(&KeyChord::parse(chord).unwrap(), 0.0)
+    }
+
+    #[test]
+    fn test_directional_focus() {
+        let mut engine = engine();
+        let left = window_at(&mut engine, 0.0, 400.0);
+        let right = window_at(&mut engine, 800.0, 400.0);
+        // Closer, but far off to the side
+        let below_right = window_at(&mut engine, 500.0, 900.0);
+        engine.focus_window(left);
+
+        assert_eq!(press(&mut engine, "ArrowRight"), KeyResult::Handled);
+        assert_eq!(engine.windows.focused(), Some(right));
+        assert_eq!(press(&mut engine, "ArrowDown"), KeyResult::Handled);
+        assert_eq!(engine.windows.focused(), Some(below_right));
+
+        // Nothing further down
+        assert_eq!(press(&mut engine, "ArrowDown"), KeyResult::Unhandled);
+        assert_eq!(engine.windows.focused(), Some(below_right));
+    }
+
+    #[test]
+    fn test_move_and_resize_by_steps() {
+        let mut engine = engine();
+        let id = window_at(&mut engine, 100.0, 100.0);
+
+        press(&mut engine, "Alt+ArrowRight");
+        press(&mut engine, "Alt+ArrowUp");
+        press(&mut engine, "Alt+Shift+ArrowRight");
+        press(&mut engine, "Alt+Shift+ArrowUp");
+
+        let window = engine.windows.get(id).unwrap();
+        assert_eq!(
+            window.position,
+            Vec2::new(100.0 + MOVE_STEP, 100.0 - MOVE_STEP)
+        );
+        assert_eq!(
+            window.size,
+            Size::new(300.0 + RESIZE_STEP, 200.0 - RESIZE_STEP)
+        );
+    }
+
+    #[test]
+    fn test_desktop_and_void_commands() {
+        let mut engine = engine();
+        engine.create_desktop("Second");
+
+        press(&mut engine, "Ctrl+ArrowLeft");
+        assert_eq!(engine.desktops.active_index(), 1);
+        engine.tick_transition(10_000.0);
+
+        assert_eq!(press(&mut engine, "F3"), KeyResult::Handled);
+        engine.tick_transition(20_000.0);
+        assert!(engine.is_in_void());
+
+        // Switching from the void leaves it
+        press(&mut engine, "Ctrl+ArrowRight");
+        engine.tick_transition(30_000.0);
+        assert!(!engine.is_in_void());
+        assert_eq!(engine.desktops.active_index(), 0);
+    }
+
+    #[test]
+    fn test_fullscreen_keeps_keys_for_app() {
+        let mut engine = engine();
+        let id = window_at(&mut engine, 100.0, 100.0);
+
+        assert_eq!(press(&mut engine, "Escape"), KeyResult::Unhandled);
+        press(&mut engine, "F11");
+        assert_eq!(engine.fullscreen_window(), Some(id));
+
+        assert_eq!(press(&mut engine, "Alt+ArrowLeft"), KeyResult::Unhandled);
+        assert_eq!(press(&mut engine, "t"), KeyResult::Unhandled);
+        assert_eq!(press(&mut engine, "Escape"), KeyResult::Handled);
+        assert_eq!(engine.fullscreen_window(), None);
+    }
+
+    #[test]
+    fn test_frontend_commands() {
+        let mut engine = engine();
+        assert_eq!(
+            press(&mut engine, "t"),
+            KeyResult::Frontend(KeyCommand::LaunchTerminal)
+        );
+        assert_eq!(press(&mut engine, "x"), KeyResult::Unhandled);
+
+        engine.hotkeys_mut().unbind(&KeyChord::parse("t").unwrap());
+        assert_eq!(press(&mut engine, "t"), KeyResult::Unhandled);
+    }
+}
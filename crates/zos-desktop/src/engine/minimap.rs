@@ -0,0 +1,191 @@
+//! Minimap of the active desktop
+//!
+//! The minimap is an overview of the active desktop's canvas: its windows
+//! and the part currently on screen. Layout is only computed while the
+//! minimap is shown, and all rects are normalized to the minimap's extent
+//! (`0.0..=1.0` on both axes) so the frontend can draw at any size.
+//!
+//! The extent covers the desktop bounds and every window on it, but not the
+//! viewport, so it stays put while the user drags across the minimap.
+
+use super::DesktopEngine;
+use crate::math::{Rect, Vec2};
+use crate::window::{WindowId, WindowState};
+
+/// Margin around the minimap extent, as a fraction of its size
+const EXTENT_MARGIN: f32 = 0.05;
+
+/// A window on the minimap
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinimapWindow {
+    pub id: WindowId,
+    /// Normalized rect within the minimap
+    pub rect: Rect,
+    pub focused: bool,
+}
+
+/// Minimap contents for one frame
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinimapLayout {
+    /// Canvas area the minimap covers
+    pub extent: Rect,
+    /// Windows back to front, in normalized coordinates
+    pub windows: Vec<MinimapWindow>,
+    /// Visible part of the canvas, in normalized coordinates (may extend
+    /// past the minimap when the camera is far out)
+    pub viewport: Rect,
+}
+
+impl MinimapLayout {
+    /// Width / height of the extent, for sizing the minimap widget
+    #[inline]
+    pub fn aspect_ratio(&self) -> f32 {
+        self.extent.width / self.extent.height
+    }
+}
+
+impl DesktopEngine {
+    /// Show or hide the minimap
+    pub fn set_minimap_visible(&mut self, visible: bool) {
+        self.minimap_visible = visible;
+    }
+
+    /// Whether the minimap is shown
+    #[inline]
+    pub fn is_minimap_visible(&self) -> bool {
+        self.minimap_visible
+    }
+
+    /// Layout of the active desktop for the minimap
+    ///
+    /// Returns `None` while the minimap is hidden or the void is shown.
+    pub fn minimap_layout(&self) -> Option<MinimapLayout> {
+        if !self.minimap_visible || !self.view_mode.is_desktop() {
+            return None;
+        }
+
+        let extent = self.minimap_extent();
+        let focused = self.windows.focused();
+        let desktop = self.desktops.active_desktop();
+        let windows = self
+            .windows
+            .windows_by_z()
+            .into_iter()
+            .filter(|w| desktop.contains_window(w.id) && w.state != WindowState::Minimized)
+            .map(|w| MinimapWindow {
+                id: w.id,
+                rect: normalize(w.rect(), extent),
+                focused: focused == Some(w.id),
+            })
+            .collect();
+
+        Some(MinimapLayout {
+            extent,
+            windows,
+            viewport: normalize(self.viewport.visible_rect(), extent),
+        })
+    }
+
+    /// Center the camera on a point of the minimap
+    ///
+    /// `x` and `y` are normalized minimap coordinates, as sent by a click
+    /// or each move of a drag. Points outside the minimap are clamped.
+    pub fn minimap_navigate(&mut self, x: f32, y: f32) {
+        if !self.view_mode.is_desktop() || self.is_crossfading() || self.is_fullscreen_active() {
+            return;
+        }
+        if !x.is_finite() || !y.is_finite() {
+            return;
+        }
+
+        let extent = self.minimap_extent();
+        self.camera_animation = None;
+        self.viewport.center = Vec2::new(
+            extent.x + x.clamp(0.0, 1.0) * extent.width,
+            extent.y + y.clamp(0.0, 1.0) * extent.height,
+        );
+        self.commit_viewport_to_desktop();
+    }
+
+    /// Canvas area covered by the minimap
+    fn minimap_extent(&self) -> Rect {
+        let desktop = self.desktops.active_desktop();
+        let content = self
+            .windows
+            .all_windows()
+            .filter(|w| desktop.contains_window(w.id) && w.state != WindowState::Minimized)
+            .fold(desktop.bounds, |acc, w| acc.union(&w.rect()));
+        let margin = content.width.max(content.height) * EXTENT_MARGIN;
+        content.expand(margin)
+    }
+}
+
+/// Map a canvas rect into `extent`'s normalized space
+fn normalize(rect: Rect, extent: Rect) -> Rect {
+    Rect::new(
+        (rect.x - extent.x) / extent.width,
+        (rect.y - extent.y) / extent.height,
+        rect.width / extent.width,
+        rect.height / extent.height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Size;
+    use crate::window::WindowConfig;
+
+    fn engine() -> DesktopEngine {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        engine
+    }
+
+    #[test]
+    fn test_hidden_minimap_has_no_layout() {
+        let mut engine = engine();
+        assert!(engine.minimap_layout().is_none());
+        engine.set_minimap_visible(true);
+        assert!(engine.minimap_layout().is_some());
+        engine.enter_void(0.0);
+        engine.tick_transition(10_000.0);
+        assert!(engine.minimap_layout().is_none());
+    }
+
+    #[test]
+    fn test_layout_is_normalized() {
+        let mut engine = engine();
+        engine.set_minimap_visible(true);
+        let far = engine.create_window(WindowConfig {
+            position: Some(Vec2::new(10_000.0, 10_000.0)),
+            size: Size::new(800.0, 600.0),
+            ..Default::default()
+        });
+
+        let layout = engine.minimap_layout().unwrap();
+        let window = layout.windows.iter().find(|w| w.id == far).unwrap();
+        assert!(window.focused);
+        for r in [window.rect, layout.viewport] {
+            assert!(r.x >= 0.0 && r.y >= 0.0);
+            assert!(r.right() <= 1.0 && r.bottom() <= 1.0);
+        }
+        // The far window sits in the bottom-right of the minimap
+        assert!(window.rect.center().x > 0.8 && window.rect.center().y > 0.8);
+    }
+
+    #[test]
+    fn test_navigate_centers_camera() {
+        let mut engine = engine();
+        engine.set_minimap_visible(true);
+        let extent = engine.minimap_layout().unwrap().extent;
+
+        engine.minimap_navigate(0.5, 0.5);
+        assert_eq!(engine.viewport.center, extent.center());
+
+        engine.minimap_navigate(2.0, -1.0);
+        assert_eq!(engine.viewport.center, Vec2::new(extent.right(), extent.y));
+        let layout = engine.minimap_layout().unwrap();
+        assert!((layout.viewport.center().x - 1.0).abs() < 0.001);
+    }
+}
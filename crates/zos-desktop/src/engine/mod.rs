@@ -11,9 +11,20 @@
 //! | `windows.rs`        | Window lifecycle: `create_window`, `close_window`, `focus_window`, `move_window`, `resize_window`, `launch_app` |
 //! | `pointer_events.rs` | Input handling: `handle_pointer_down`, `handle_pointer_move`, `handle_pointer_up`, `handle_wheel` |
 //! | `void_mode.rs`      | Void transitions: `enter_void`, `exit_void`               |
+//! | `desktops.rs`       | Desktop tiles: `rename_desktop`, `move_desktop`, `delete_desktop`, `duplicate_desktop`, `create_desktop_from_template`, `set_desktop_folders`, `export_snapshot` |
 //! | `transitions.rs`    | Animation ticking: `tick_transition`, `layer_opacities`, `is_crossfading`, `is_animating` |
-//! | `animation.rs`      | Camera animation: `pan_to_window`                         |
-//! | `rendering.rs`      | Screen calculations: `get_window_screen_rects`            |
+//! | `annotations.rs`    | Pen annotations: `begin_stroke`, `extend_stroke`, `end_stroke`, `undo_stroke`, `annotations` |
+//! | `widgets.rs`        | Canvas widgets: `add_widget`, `update_widget`, `move_widget`, `remove_widget`, `widget_at` |
+//! | `animation.rs`      | Camera animation: `pan_to_window`, `zoom_to_window`, `zoom_to_fit_all`, `zoom_to_rect` |
+//! | `focus.rs`          | Focus requests: `request_focus`, `set_focus_policy`        |
+//! | `fullscreen.rs`     | Fullscreen: `enter_fullscreen`, `exit_fullscreen`, `fullscreen_window` |
+//! | `rendering.rs`      | Screen calculations: `get_window_screen_rects`, `get_widget_screen_rects` |
+//! | `minimap.rs`        | Minimap: `minimap_layout`, `minimap_navigate`, `set_minimap_visible` |
+//! | `rules.rs`          | Window rules: `apply_window_rules`, `add_window_rule`, `set_window_rules` |
+//! | `keyboard.rs`       | Hotkeys: `handle_key`, `run_key_command`, `focus_in_direction`, `cheat_sheet` |
+//! | `recording.rs`      | Input recording: `dispatch_input`, `start_input_recording`, `stop_input_recording`, `replay_input` |
+//! | `accessibility.rs`  | Display settings: `set_display_settings`, `display_settings` (reduced motion gates animations) |
+//! | `script/runner.rs`  | Automation: `run_script`, `apply_script_op` (in [`crate::script`]) |
 //!
 //! ## Invariants
 //!
@@ -21,6 +32,7 @@
 //! - During crossfades, `view_mode` represents the *destination* state
 //! - Only one crossfade or camera animation can be active at a time
 //! - The active desktop index is always valid
+//! - At most one window per desktop is fullscreen, and none while in the void
 //!
 //! ## State Transitions
 //!
@@ -36,23 +48,42 @@
 //! - Void/desktop transitions are blocked during active crossfades
 //! - Desktop switches can interrupt other desktop switches (for responsiveness)
 
+mod accessibility;
 mod animation;
+mod annotations;
+mod desktops;
+mod focus;
+mod fullscreen;
+mod keyboard;
+mod minimap;
 mod pointer_events;
+mod recording;
 mod rendering;
+mod rules;
 mod transitions;
 mod void_mode;
+mod widgets;
 mod windows;
 
+use crate::accessibility::DisplaySettings;
+use crate::annotation::AnnotationTool;
 use crate::desktop::{DesktopManager, VoidState};
-use crate::input::InputRouter;
+use crate::input::{InputRecorder, InputRouter};
+use crate::keyboard::HotkeyRegistry;
 use crate::math::{Camera, Rect, Size};
 use crate::transition::{CameraAnimation, Crossfade};
 use crate::desktop::ViewMode;
+use crate::rules::WindowRules;
 use crate::viewport::Viewport;
+use crate::widget::WidgetId;
 use crate::window::{WindowId, WindowManager, WindowState};
 use std::collections::HashMap;
 
-pub use rendering::WindowScreenRect;
+use annotations::AnnotationGesture;
+
+pub use focus::FocusPolicy;
+pub use minimap::{MinimapLayout, MinimapWindow};
+pub use rendering::{WidgetScreenRect, WindowScreenRect};
 
 /// Desktop engine coordinating all desktop components
 ///
@@ -89,6 +120,26 @@ pub struct DesktopEngine {
     pub(crate) last_activity_ms: f64,
     /// Per-window camera memory (remembers camera position for each window)
     pub(crate) window_cameras: HashMap<WindowId, Camera>,
+    /// Rules applied to new windows
+    pub(crate) rules: WindowRules,
+    /// Whether the minimap is shown (layout is only computed then)
+    pub(crate) minimap_visible: bool,
+    /// How focus requests reach windows on other desktops
+    pub(crate) focus_policy: FocusPolicy,
+    /// Selected annotation tool (`None` when not annotating)
+    pub(crate) annotation_tool: Option<AnnotationTool>,
+    /// Stroke or erase gesture in progress
+    pub(crate) annotation_gesture: Option<AnnotationGesture>,
+    /// Bumped on every annotation change so the frontend knows to redraw
+    pub(crate) annotation_revision: u64,
+    /// Last widget ID handed out (IDs are unique across desktops)
+    pub(crate) next_widget_id: WidgetId,
+    /// Accessibility display settings (reduced motion skips animations)
+    pub(crate) display: DisplaySettings,
+    /// Hotkey bindings for keyboard window management
+    pub(crate) hotkeys: HotkeyRegistry,
+    /// Input recording in progress
+    pub(crate) input_recorder: Option<InputRecorder>,
 }
 
 impl Default for DesktopEngine {
@@ -111,6 +162,16 @@ impl DesktopEngine {
             camera_animation: None,
             last_activity_ms: 0.0,
             window_cameras: HashMap::new(),
+            rules: WindowRules::new(),
+            minimap_visible: false,
+            focus_policy: FocusPolicy::default(),
+            annotation_tool: None,
+            annotation_gesture: None,
+            annotation_revision: 0,
+            next_widget_id: 0,
+            display: DisplaySettings::default(),
+            hotkeys: HotkeyRegistry::default(),
+            input_recorder: None,
         }
     }
 
@@ -141,11 +202,14 @@ impl DesktopEngine {
         let min_height = height.max(1080.0);
         self.desktops
             .set_desktop_size(Size::new(min_width, min_height));
+        self.refit_fullscreen_windows();
     }
 
     /// Pan the viewport
+    ///
+    /// Ignored while a fullscreen window covers the screen.
     pub fn pan(&mut self, dx: f32, dy: f32) {
-        if self.is_crossfading() {
+        if self.is_crossfading() || self.is_fullscreen_active() {
             return;
         }
 
@@ -163,8 +227,10 @@ impl DesktopEngine {
     }
 
     /// Zoom at anchor point
+    ///
+    /// Ignored while a fullscreen window covers the screen.
     pub fn zoom_at(&mut self, factor: f32, anchor_x: f32, anchor_y: f32) {
-        if self.is_crossfading() {
+        if self.is_crossfading() || self.is_fullscreen_active() {
             return;
         }
 
@@ -2,7 +2,7 @@
 
 use super::DesktopEngine;
 use crate::input::{DragState, InputResult};
-use crate::math::Vec2;
+use crate::math::{Size, Vec2};
 use crate::window::{WindowId, WindowRegion};
 
 impl DesktopEngine {
@@ -45,7 +45,7 @@ impl DesktopEngine {
             _ => return,
         };
 
-        if let Some(window) = self.windows.get(id) {
+        if let Some(window) = self.windows.get(id).filter(|w| w.is_resizable()) {
             let canvas_pos = self
                 .viewport
                 .screen_to_canvas(Vec2::new(screen_x, screen_y));
@@ -54,6 +54,42 @@ impl DesktopEngine {
         }
     }
 
+    /// Start carrying a window over the desktop tiles in the void
+    ///
+    /// Releasing the pointer over a tile moves the window to that desktop
+    /// (see [`DesktopEngine::move_window_to_desktop`]).
+    pub fn start_window_carry(&mut self, id: WindowId, screen_x: f32, screen_y: f32) {
+        if !self.view_mode.is_void() || self.is_crossfading() || self.windows.get(id).is_none() {
+            return;
+        }
+        self.input
+            .start_window_carry(id, Vec2::new(screen_x, screen_y));
+    }
+
+    /// Index of the desktop tile under a screen point in the void
+    pub fn desktop_tile_at(&self, screen_x: f32, screen_y: f32) -> Option<usize> {
+        if !self.view_mode.is_void() {
+            return None;
+        }
+        let point = self
+            .viewport
+            .screen_to_canvas(Vec2::new(screen_x, screen_y));
+        self.desktops
+            .desktops()
+            .iter()
+            .position(|d| d.bounds.contains(point))
+    }
+
+    /// Desktop tile a carried window would be dropped on, for highlighting
+    pub fn carry_drop_target(&self) -> Option<usize> {
+        match self.input.drag_state() {
+            Some(DragState::CarryWindow { pointer, .. }) => {
+                self.desktop_tile_at(pointer.x, pointer.y)
+            }
+            _ => None,
+        }
+    }
+
     /// Handle pointer down
     pub fn handle_pointer_down(
         &mut self,
@@ -66,8 +102,10 @@ impl DesktopEngine {
         let screen_pos = Vec2::new(x, y);
         let canvas_pos = self.viewport.screen_to_canvas(screen_pos);
 
-        // Middle mouse or ctrl/shift + left = pan
-        if button == 1 || (button == 0 && (ctrl || shift)) {
+        // Middle mouse or ctrl/shift + left = pan (the camera is locked
+        // while a window is fullscreen, so these go to the app instead)
+        let pan_gesture = button == 1 || (button == 0 && (ctrl || shift));
+        if pan_gesture && !self.is_fullscreen_active() {
             self.camera_animation = None;
             self.input.start_pan(screen_pos, self.viewport.center);
             return InputResult::Handled;
@@ -92,7 +130,7 @@ impl DesktopEngine {
                 .region_at_filtered(canvas_pos, Some(active_windows), zoom)
             {
                 Some(hit) => hit,
-                None => return InputResult::Unhandled,
+                None => return self.handle_canvas_click(canvas_pos),
             };
 
         match region {
@@ -115,6 +153,17 @@ impl DesktopEngine {
         }
     }
 
+    /// Handle click on the canvas outside windows - widgets or nothing
+    fn handle_canvas_click(&mut self, canvas_pos: Vec2) -> InputResult {
+        if !self.view_mode.is_desktop() {
+            return InputResult::Unhandled;
+        }
+        match self.desktops.active_desktop().widgets.widget_at(canvas_pos) {
+            Some(widget_id) => self.handle_widget_press(widget_id, canvas_pos),
+            None => InputResult::Unhandled,
+        }
+    }
+
     /// Handle click on title bar - starts window move
     fn handle_title_bar_click(&mut self, window_id: WindowId, canvas_pos: Vec2) -> InputResult {
         self.camera_animation = None;
@@ -137,7 +186,10 @@ impl DesktopEngine {
 
         // If content_interactive is false, clicking/dragging content moves the window
         // If content_interactive is true, forward events to the app
-        if !window.content_interactive {
+        // Fullscreen windows can't be moved, so non-interactive content just takes focus
+        if window.is_fullscreen() && !window.content_interactive {
+            InputResult::Handled
+        } else if !window.content_interactive {
             self.camera_animation = None;
             self.input
                 .start_window_move(window_id, canvas_pos - window.position);
@@ -178,6 +230,11 @@ impl DesktopEngine {
         let screen_pos = Vec2::new(x, y);
         let canvas_pos = self.viewport.screen_to_canvas(screen_pos);
 
+        if self.input.drag_state().is_some_and(|d| d.is_carry()) {
+            self.input.update_carry_pointer(screen_pos);
+            return InputResult::Handled;
+        }
+
         let drag_state = match self.input.drag_state() {
             Some(state) => state,
             None => return InputResult::Unhandled,
@@ -206,13 +263,40 @@ impl DesktopEngine {
                 start_mouse,
             } => {
                 let delta = canvas_pos - *start_mouse;
-                let (new_pos, new_size) =
-                    crate::input::calculate_resize(*handle, *start_pos, *start_size, delta);
                 let wid = *window_id;
+                let constraints = match self.windows.get(wid) {
+                    Some(window) => window.resize_constraints(),
+                    None => return InputResult::Unhandled,
+                };
+                let (new_pos, new_size) = crate::input::calculate_resize(
+                    *handle,
+                    *start_pos,
+                    *start_size,
+                    delta,
+                    &constraints,
+                );
                 self.move_window(wid, new_pos.x, new_pos.y);
                 self.resize_window(wid, new_size.width, new_size.height);
                 InputResult::Handled
             }
+            DragState::CarryWindow { .. } => InputResult::Handled,
+            DragState::MoveWidget { widget_id, offset } => {
+                let new_pos = canvas_pos - *offset;
+                let wid = *widget_id;
+                let _ = self.move_widget(wid, new_pos);
+                InputResult::Handled
+            }
+            DragState::ResizeWidget {
+                widget_id,
+                start_size,
+                start_mouse,
+            } => {
+                let delta = canvas_pos - *start_mouse;
+                let new_size = Size::new(start_size.width + delta.x, start_size.height + delta.y);
+                let wid = *widget_id;
+                let _ = self.resize_widget(wid, new_size);
+                InputResult::Handled
+            }
         }
     }
 
@@ -220,11 +304,20 @@ impl DesktopEngine {
     pub fn handle_pointer_up(&mut self) -> InputResult {
         if self.input.is_dragging() {
             let was_pan = matches!(self.input.drag_state(), Some(DragState::PanCanvas { .. }));
+            let carry = match self.input.drag_state() {
+                Some(DragState::CarryWindow { window_id, .. }) => {
+                    Some((*window_id, self.carry_drop_target()))
+                }
+                _ => None,
+            };
             self.input.end_drag();
 
             if was_pan {
                 self.commit_viewport_to_desktop();
             }
+            if let Some((window_id, Some(target))) = carry {
+                let _ = self.move_window_to_desktop(window_id, target);
+            }
 
             return InputResult::Handled;
         }
@@ -233,7 +326,7 @@ impl DesktopEngine {
 
     /// Handle wheel event
     pub fn handle_wheel(&mut self, _dx: f32, dy: f32, x: f32, y: f32, ctrl: bool) -> InputResult {
-        if ctrl {
+        if ctrl && !self.is_fullscreen_active() {
             let factor = if dy < 0.0 { 1.1 } else { 0.9 };
             self.zoom_at(factor, x, y);
             InputResult::Handled
@@ -410,6 +503,26 @@ mod tests {
         assert!(!engine.input.is_dragging());
     }
 
+    #[test]
+    fn test_carry_window_to_desktop_tile() {
+        let mut engine = create_test_engine();
+        let id = create_test_window(&mut engine, 100.0, 100.0);
+        engine.create_desktop("Second");
+        engine.enter_void(0.0);
+        engine.tick_transition(10_000.0);
+
+        let tile = engine.desktops.desktops()[1].bounds.center();
+        let screen = engine.viewport.canvas_to_screen(tile);
+        engine.start_window_carry(id, 0.0, 0.0);
+        engine.handle_pointer_move(screen.x, screen.y);
+        assert_eq!(engine.carry_drop_target(), Some(1));
+
+        engine.handle_pointer_up();
+        assert!(!engine.input.is_dragging());
+        assert!(engine.desktops.desktops()[1].contains_window(id));
+        assert!(!engine.desktops.desktops()[0].contains_window(id));
+    }
+
     #[test]
     fn test_camera_animation_cancelled_on_pan() {
         let mut engine = create_test_engine();
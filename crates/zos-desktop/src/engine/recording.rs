@@ -0,0 +1,153 @@
+//! Input recording and replay
+//!
+//! Live input enters through `dispatch_input`, which records the event when
+//! a recording is running and then routes it to the pointer handlers.
+
+use super::DesktopEngine;
+use crate::input::{InputEvent, InputRecorder, InputRecording, InputResult};
+use crate::script::{ScriptClock, ScriptReport};
+use tracing::info;
+
+impl DesktopEngine {
+    /// Handle a raw input event, recording it if a recording is running
+    ///
+    /// Drag starts always report `Handled`.
+    pub fn dispatch_input(&mut self, event: &InputEvent, now_ms: f64) -> InputResult {
+        if let Some(recorder) = self.input_recorder.as_mut() {
+            recorder.record(event.clone(), now_ms);
+        }
+        match *event {
+            InputEvent::PointerDown {
+                x,
+                y,
+                button,
+                ctrl,
+                shift,
+            } => self.handle_pointer_down(x, y, button, ctrl, shift),
+            InputEvent::PointerMove { x, y } => self.handle_pointer_move(x, y),
+            InputEvent::PointerUp => self.handle_pointer_up(),
+            InputEvent::Wheel { dx, dy, x, y, ctrl } => self.handle_wheel(dx, dy, x, y, ctrl),
+            InputEvent::StartWindowDrag { window, x, y } => {
+                self.start_move_drag(window, x, y);
+                InputResult::Handled
+            }
+            InputEvent::StartWindowResize {
+                window,
+                ref direction,
+                x,
+                y,
+            } => {
+                self.start_resize_drag(window, direction, x, y);
+                InputResult::Handled
+            }
+            InputEvent::StartWindowCarry { window, x, y } => {
+                self.start_window_carry(window, x, y);
+                InputResult::Handled
+            }
+        }
+    }
+
+    /// Start recording input (restarts a recording already running)
+    pub fn start_input_recording(&mut self, now_ms: f64) {
+        info!("input recording started");
+        self.input_recorder = Some(InputRecorder::new(now_ms));
+    }
+
+    /// Stop recording and return what was captured
+    pub fn stop_input_recording(&mut self) -> Option<InputRecording> {
+        let recorder = self.input_recorder.take()?;
+        info!(events = recorder.len(), "input recording stopped");
+        Some(recorder.finish(self.viewport.screen_size))
+    }
+
+    /// Whether input is being recorded
+    #[inline]
+    pub fn is_recording_input(&self) -> bool {
+        self.input_recorder.is_some()
+    }
+
+    /// Replay a recording on the virtual clock
+    ///
+    /// Replays assume the screen size and starting state of the recording;
+    /// set those up first (e.g. with a script) for a faithful reproduction.
+    pub fn replay_input(
+        &mut self,
+        clock: &mut ScriptClock,
+        recording: &InputRecording,
+    ) -> ScriptReport {
+        self.run_script(clock, &recording.to_script())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::ScriptOp;
+    use crate::Vec2;
+
+    fn engine_with_window() -> (DesktopEngine, ScriptClock) {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        let mut clock = ScriptClock::default();
+        let setup = [ScriptOp::CreateWindow {
+            title: "Notes".to_string(),
+            app_id: "notes".to_string(),
+            x: Some(100.0),
+            y: Some(100.0),
+            width: 400.0,
+            height: 300.0,
+        }];
+        assert!(engine.run_script(&mut clock, &setup).is_ok());
+        (engine, clock)
+    }
+
+    #[test]
+    fn test_record_and_replay_drag() {
+        let (mut engine, _) = engine_with_window();
+        let window = engine.windows.all_windows().next().unwrap().id;
+        let start = engine.windows.get(window).unwrap().position;
+        let grab = engine
+            .viewport
+            .canvas_to_screen(start + Vec2::new(50.0, 10.0));
+
+        // Drag by the title bar while recording
+        engine.start_input_recording(5_000.0);
+        let events = [
+            InputEvent::PointerDown {
+                x: grab.x,
+                y: grab.y,
+                button: 0,
+                ctrl: false,
+                shift: false,
+            },
+            InputEvent::PointerMove {
+                x: grab.x + 60.0,
+                y: grab.y + 30.0,
+            },
+            InputEvent::PointerUp,
+        ];
+        for (i, event) in events.iter().enumerate() {
+            engine.dispatch_input(event, 5_000.0 + i as f64 * 16.0);
+        }
+        let recording = engine.stop_input_recording().unwrap();
+        assert!(!engine.is_recording_input());
+        assert_eq!(recording.events.len(), 3);
+        assert_eq!(recording.duration_ms(), 32.0);
+
+        let moved = engine.windows.get(window).unwrap().position;
+        assert_ne!(moved, start);
+
+        // Replaying from the same starting state ends in the same place
+        let (mut fresh, mut clock) = engine_with_window();
+        assert!(fresh.replay_input(&mut clock, &recording).is_ok());
+        assert_eq!(clock.now_ms(), 32.0);
+        assert_eq!(fresh.windows.get(window).unwrap().position, moved);
+    }
+
+    #[test]
+    fn test_stop_without_recording() {
+        let (mut engine, _) = engine_with_window();
+        engine.dispatch_input(&InputEvent::PointerUp, 0.0);
+        assert_eq!(engine.stop_input_recording(), None);
+    }
+}
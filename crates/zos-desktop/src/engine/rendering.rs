@@ -1,7 +1,8 @@
-//! Window rendering and screen coordinate calculations
+//! Window and widget rendering and screen coordinate calculations
 
 use super::DesktopEngine;
 use crate::math::Rect;
+use crate::widget::{WidgetId, WidgetKind};
 use crate::window::{WindowId, WindowState, WindowType};
 
 /// Window with screen-space coordinates for rendering
@@ -20,9 +21,48 @@ pub struct WindowScreenRect {
     pub opacity: f32,
     /// Whether the window content area handles its own mouse events
     pub content_interactive: bool,
+    /// Whether resize handles should be shown
+    pub resizable: bool,
+}
+
+/// Widget with screen-space coordinates for rendering
+#[derive(Clone, Debug)]
+pub struct WidgetScreenRect {
+    pub id: WidgetId,
+    pub kind: WidgetKind,
+    pub screen_rect: Rect,
+    /// Opacity for fade transitions, as for windows
+    pub opacity: f32,
 }
 
 impl DesktopEngine {
+    /// Get widget screen rects for rendering, back to front
+    ///
+    /// Widgets sit below all windows of the same desktop.
+    pub fn get_widget_screen_rects(&self, now_ms: f64) -> Vec<WidgetScreenRect> {
+        let workspace_index = self.get_visual_active_workspace_at(now_ms);
+        let Some(workspace) = self.desktops.desktops().get(workspace_index) else {
+            return Vec::new();
+        };
+
+        let opacity = self.calculate_window_opacity(now_ms);
+        workspace
+            .widgets
+            .widgets()
+            .iter()
+            .map(|w| {
+                let pos = self.viewport.canvas_to_screen(w.position);
+                let size = w.size.scale(self.viewport.zoom);
+                WidgetScreenRect {
+                    id: w.id,
+                    kind: w.kind.clone(),
+                    screen_rect: Rect::new(pos.x, pos.y, size.width, size.height),
+                    opacity,
+                }
+            })
+            .collect()
+    }
+
     /// Get window screen rects for rendering
     pub fn get_window_screen_rects(&self, now_ms: f64) -> Vec<WindowScreenRect> {
         let workspace_index = self.get_visual_active_workspace_at(now_ms);
@@ -68,6 +108,7 @@ impl DesktopEngine {
             ),
             opacity,
             content_interactive: w.content_interactive,
+            resizable: w.is_resizable() && !w.is_fullscreen(),
         }
     }
 
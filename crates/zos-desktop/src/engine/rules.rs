@@ -0,0 +1,200 @@
+//! Window rule evaluation and editing
+
+use super::DesktopEngine;
+use crate::desktop::ViewMode;
+use crate::error::DesktopResult;
+use crate::math::{Size, Vec2};
+use crate::rules::{Placement, RuleActions, RuleId, WindowRule, WindowRules};
+use crate::window::WindowConfig;
+use tracing::{debug, info};
+
+impl DesktopEngine {
+    /// The current window rules
+    #[inline]
+    pub fn window_rules(&self) -> &WindowRules {
+        &self.rules
+    }
+
+    /// Replace all window rules (e.g. after loading them from settings)
+    pub fn set_window_rules(&mut self, rules: WindowRules) {
+        info!(count = rules.rules().len(), "window rules loaded");
+        self.rules = rules;
+    }
+
+    /// Append a window rule
+    pub fn add_window_rule(&mut self, rule: WindowRule) -> DesktopResult<RuleId> {
+        self.rules.add(rule)
+    }
+
+    /// Replace a window rule by ID
+    pub fn update_window_rule(&mut self, rule: WindowRule) -> DesktopResult<()> {
+        self.rules.update(rule)
+    }
+
+    /// Remove a window rule
+    pub fn remove_window_rule(&mut self, id: RuleId) -> DesktopResult<()> {
+        self.rules.remove(id).map(|_| ())
+    }
+
+    /// Move a window rule to a new evaluation position
+    pub fn reorder_window_rule(&mut self, id: RuleId, index: usize) -> DesktopResult<()> {
+        self.rules.reorder(id, index)
+    }
+
+    /// Apply matching rules to a window about to be created
+    ///
+    /// Returns the index of the desktop the window should open on.
+    pub(crate) fn apply_window_rules(&self, config: &mut WindowConfig) -> usize {
+        let active = self.desktops.active_index();
+        let actions = self.rules.resolve(config);
+        if actions.is_empty() {
+            return active;
+        }
+        debug!(app_id = %config.app_id, ?actions, "window rules matched");
+
+        let desktop = actions
+            .desktop
+            .filter(|&index| index < self.desktops.count())
+            .unwrap_or(active);
+
+        let screen = self.viewport.screen_size;
+        if let Some(width) = actions.width {
+            config.size.width = width.resolve(screen.width);
+        }
+        if let Some(height) = actions.height {
+            config.size.height = height.resolve(screen.height);
+        }
+        if let Some(min) = config.min_size {
+            config.size = Size::new(
+                config.size.width.max(min.width),
+                config.size.height.max(min.height),
+            );
+        }
+
+        match actions.placement {
+            Some(Placement::Cascade) => config.position = None,
+            Some(Placement::Centered) => {
+                let center = self.desktop_view_center(desktop);
+                config.position = Some(center - config.size.as_vec2() / 2.0);
+            }
+            Some(Placement::At { x, y }) => config.position = Some(Vec2::new(x, y)),
+            None => {}
+        }
+
+        apply_resizable(&actions, config);
+        desktop
+    }
+
+    /// Canvas point at the center of a desktop's view
+    pub(crate) fn desktop_view_center(&self, index: usize) -> Vec2 {
+        match self.view_mode {
+            ViewMode::Desktop { index: shown } if shown == index => self.viewport.center,
+            _ => self
+                .desktops
+                .get_desktop_camera(index)
+                .map(|camera| camera.center)
+                .unwrap_or(self.viewport.center),
+        }
+    }
+}
+
+fn apply_resizable(actions: &RuleActions, config: &mut WindowConfig) {
+    match actions.resizable {
+        Some(false) => {
+            config.min_size = Some(config.size);
+            config.max_size = Some(config.size);
+        }
+        Some(true) => config.max_size = None,
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Length, RuleMatch};
+
+    fn engine_with_rule(app_id: &str, actions: RuleActions) -> DesktopEngine {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        engine.create_desktop("Second");
+        engine
+            .add_window_rule(WindowRule::new(
+                app_id,
+                RuleMatch {
+                    app_id: Some(app_id.to_string()),
+                    ..Default::default()
+                },
+                actions,
+            ))
+            .unwrap();
+        engine
+    }
+
+    #[test]
+    fn test_rule_places_window_on_desktop_at_half_width() {
+        let mut engine = engine_with_rule(
+            "terminal",
+            RuleActions {
+                desktop: Some(1),
+                width: Some(Length::Percent(50.0)),
+                ..Default::default()
+            },
+        );
+        let id = engine.launch_app("terminal");
+
+        assert!(engine.desktops.desktops()[1].contains_window(id));
+        assert!(!engine.desktops.active_desktop().contains_window(id));
+        assert_eq!(engine.windows.get(id).unwrap().size.width, 960.0);
+    }
+
+    #[test]
+    fn test_rule_centers_and_locks_size() {
+        let mut engine = engine_with_rule(
+            "settings",
+            RuleActions {
+                placement: Some(Placement::Centered),
+                resizable: Some(false),
+                ..Default::default()
+            },
+        );
+        engine.viewport.center = Vec2::new(300.0, 200.0);
+        let id = engine.launch_app("settings");
+
+        let window = engine.windows.get(id).unwrap();
+        assert_eq!(window.rect().center(), Vec2::new(300.0, 200.0));
+        assert!(!window.is_resizable());
+
+        let size = window.size;
+        engine.resize_window(id, 2000.0, 2000.0);
+        assert_eq!(engine.windows.get(id).unwrap().size, size);
+    }
+
+    #[test]
+    fn test_rule_for_missing_desktop_uses_active() {
+        let mut engine = engine_with_rule(
+            "browser",
+            RuleActions {
+                desktop: Some(9),
+                ..Default::default()
+            },
+        );
+        let id = engine.launch_app("browser");
+        assert!(engine.desktops.active_desktop().contains_window(id));
+    }
+
+    #[test]
+    fn test_unmatched_window_untouched() {
+        let mut engine = engine_with_rule(
+            "terminal",
+            RuleActions {
+                width: Some(Length::Px(300.0)),
+                ..Default::default()
+            },
+        );
+        let id = engine.launch_app("browser");
+        let window = engine.windows.get(id).unwrap();
+        assert_eq!(window.size.width, 900.0);
+        assert!(window.is_resizable());
+    }
+}
@@ -20,6 +20,9 @@ impl DesktopEngine {
             _ => return,
         };
 
+        // The void shows desktops side by side, so nothing stays fullscreen
+        self.exit_all_fullscreen();
+
         // Save current desktop camera state
         self.desktops
             .save_desktop_camera(from_desktop, self.viewport.center, self.viewport.zoom);
@@ -31,7 +34,7 @@ impl DesktopEngine {
         self.void_state.set_camera(Camera::at(center, zoom));
 
         // Start crossfade to void
-        self.crossfade = Some(Crossfade::to_void(now_ms, from_desktop));
+        self.start_crossfade(Crossfade::to_void(now_ms, from_desktop), now_ms);
         self.last_activity_ms = now_ms;
 
         debug!(from_desktop = from_desktop, "entering void");
@@ -53,7 +56,7 @@ impl DesktopEngine {
         self.desktops.switch_to(desktop_index);
 
         // Start crossfade to desktop
-        self.crossfade = Some(Crossfade::to_desktop(now_ms, desktop_index));
+        self.start_crossfade(Crossfade::to_desktop(now_ms, desktop_index), now_ms);
         self.last_activity_ms = now_ms;
 
         debug!(target_desktop = desktop_index, "exiting void");
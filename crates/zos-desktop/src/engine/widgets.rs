@@ -0,0 +1,260 @@
+//! Canvas widget operations
+//!
+//! CRUD for widgets plus their part of pointer handling. Widgets are
+//! hit-tested after windows, so a window always wins where they overlap,
+//! and a press on a widget drags it (or resizes it from the bottom-right
+//! corner) instead of panning.
+
+use super::DesktopEngine;
+use crate::error::{DesktopError, DesktopResult};
+use crate::input::InputResult;
+use crate::math::{Size, Vec2};
+use crate::widget::{Widget, WidgetId, WidgetKind};
+use tracing::debug;
+
+/// Size of the resize corner, in screen pixels
+const RESIZE_CORNER: f32 = 16.0;
+
+impl DesktopEngine {
+    /// Add a widget to a desktop, returning its ID
+    ///
+    /// Without a position the widget is centered in the desktop's view.
+    pub fn add_widget(
+        &mut self,
+        desktop_index: usize,
+        kind: WidgetKind,
+        position: Option<Vec2>,
+    ) -> DesktopResult<WidgetId> {
+        self.check_desktop_index(desktop_index)?;
+
+        let size = kind.default_size();
+        let position = position.unwrap_or_else(|| {
+            let center = self.desktop_view_center(desktop_index);
+            Vec2::new(center.x - size.width / 2.0, center.y - size.height / 2.0)
+        });
+
+        self.next_widget_id += 1;
+        let id = self.next_widget_id;
+        let desktop_id = self.desktops.desktops()[desktop_index].id;
+        if let Some(desktop) = self.desktops.get_mut(desktop_id) {
+            desktop
+                .widgets
+                .insert(Widget::new(id, kind, position, size));
+        }
+        debug!(widget_id = id, desktop_index, "widget added");
+        Ok(id)
+    }
+
+    /// Get a widget by ID
+    pub fn widget(&self, id: WidgetId) -> Option<&Widget> {
+        self.desktops
+            .desktop_with_widget(id)
+            .and_then(|d| d.widgets.get(id))
+    }
+
+    /// Replace a widget's contents (e.g. a sticky note's text)
+    pub fn update_widget(&mut self, id: WidgetId, kind: WidgetKind) -> DesktopResult<()> {
+        self.widget_mut(id)?.kind = kind;
+        Ok(())
+    }
+
+    /// Move a widget to a canvas position
+    pub fn move_widget(&mut self, id: WidgetId, position: Vec2) -> DesktopResult<()> {
+        self.widget_mut(id)?.position = position;
+        Ok(())
+    }
+
+    /// Resize a widget (clamped to the minimum widget size)
+    pub fn resize_widget(&mut self, id: WidgetId, size: Size) -> DesktopResult<()> {
+        self.widget_mut(id)?.set_size(size);
+        Ok(())
+    }
+
+    /// Remove a widget
+    pub fn remove_widget(&mut self, id: WidgetId) -> DesktopResult<()> {
+        if self.input.drag_state().and_then(|d| d.widget_id()) == Some(id) {
+            self.input.end_drag();
+        }
+        self.desktops
+            .desktop_with_widget_mut(id)
+            .and_then(|d| d.widgets.remove(id))
+            .map(|_| debug!(widget_id = id, "widget removed"))
+            .ok_or(DesktopError::WidgetNotFound(id))
+    }
+
+    /// Topmost widget of the active desktop under a screen point
+    ///
+    /// Only widgets not covered by a window are returned.
+    pub fn widget_at(&self, screen_x: f32, screen_y: f32) -> Option<WidgetId> {
+        if !self.view_mode.is_desktop() {
+            return None;
+        }
+        let canvas_pos = self
+            .viewport
+            .screen_to_canvas(Vec2::new(screen_x, screen_y));
+        let desktop = self.desktops.active_desktop();
+        let covered = self
+            .windows
+            .region_at_filtered(canvas_pos, Some(&desktop.windows), self.viewport.zoom)
+            .is_some();
+        if covered {
+            return None;
+        }
+        desktop.widgets.widget_at(canvas_pos)
+    }
+
+    /// Start dragging a widget (for widgets whose DOM handles the press)
+    pub fn start_widget_drag(&mut self, id: WidgetId, screen_x: f32, screen_y: f32) {
+        let canvas_pos = self
+            .viewport
+            .screen_to_canvas(Vec2::new(screen_x, screen_y));
+        self.handle_widget_press(id, canvas_pos);
+    }
+
+    /// Pointer press on a widget: raise it and start a move or resize drag
+    pub(crate) fn handle_widget_press(&mut self, id: WidgetId, canvas_pos: Vec2) -> InputResult {
+        let zoom = self.viewport.zoom;
+        let Some(desktop) = self.desktops.desktop_with_widget_mut(id) else {
+            return InputResult::Unhandled;
+        };
+        desktop.widgets.raise(id);
+        let Some(widget) = desktop.widgets.get(id) else {
+            return InputResult::Unhandled;
+        };
+
+        let rect = widget.rect();
+        let corner = RESIZE_CORNER / zoom;
+        let in_corner =
+            canvas_pos.x >= rect.right() - corner && canvas_pos.y >= rect.bottom() - corner;
+
+        self.camera_animation = None;
+        if in_corner {
+            self.input.start_widget_resize(id, widget.size, canvas_pos);
+        } else {
+            self.input
+                .start_widget_move(id, canvas_pos - widget.position);
+        }
+        InputResult::Handled
+    }
+
+    fn widget_mut(&mut self, id: WidgetId) -> DesktopResult<&mut Widget> {
+        self.desktops
+            .desktop_with_widget_mut(id)
+            .and_then(|d| d.widgets.get_mut(id))
+            .ok_or(DesktopError::WidgetNotFound(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::WindowConfig;
+
+    fn engine() -> DesktopEngine {
+        let mut engine = DesktopEngine::new();
+        engine.init(1920.0, 1080.0);
+        engine
+    }
+
+    fn note(text: &str) -> WidgetKind {
+        WidgetKind::StickyNote {
+            text: text.to_string(),
+            color: "#fff".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_widget_crud() {
+        let mut engine = engine();
+        let id = engine.add_widget(0, note("todo"), None).unwrap();
+
+        // Centered in view
+        let rect = engine.widget(id).unwrap().rect();
+        assert_eq!(rect.center(), engine.viewport.center);
+
+        engine.update_widget(id, note("done")).unwrap();
+        engine.resize_widget(id, Size::new(10.0, 10.0)).unwrap();
+        let widget = engine.widget(id).unwrap();
+        assert_eq!(widget.kind, note("done"));
+        assert_eq!(widget.size, crate::widget::MIN_WIDGET_SIZE);
+
+        engine.remove_widget(id).unwrap();
+        assert_eq!(
+            engine.remove_widget(id),
+            Err(DesktopError::WidgetNotFound(id))
+        );
+        assert!(engine.add_widget(5, note(""), None).is_err());
+    }
+
+    #[test]
+    fn test_pointer_drags_widget() {
+        let mut engine = engine();
+        let id = engine
+            .add_widget(
+                0,
+                note(""),
+                Some(engine.viewport.screen_to_canvas(Vec2::new(100.0, 100.0))),
+            )
+            .unwrap();
+        let start = engine.widget(id).unwrap().position;
+
+        let result = engine.handle_pointer_down(150.0, 150.0, 0, false, false);
+        assert!(matches!(result, InputResult::Handled));
+        engine.handle_pointer_move(250.0, 170.0);
+        engine.handle_pointer_up();
+
+        let moved = engine.widget(id).unwrap().position;
+        assert_eq!(moved - start, Vec2::new(100.0, 20.0));
+    }
+
+    #[test]
+    fn test_pointer_resizes_from_corner() {
+        let mut engine = engine();
+        let origin = engine.viewport.screen_to_canvas(Vec2::new(100.0, 100.0));
+        let id = engine.add_widget(0, note(""), Some(origin)).unwrap();
+        let size = engine.widget(id).unwrap().size;
+
+        let corner = Vec2::new(100.0 + size.width - 4.0, 100.0 + size.height - 4.0);
+        engine.handle_pointer_down(corner.x, corner.y, 0, false, false);
+        engine.handle_pointer_move(corner.x + 50.0, corner.y + 30.0);
+        engine.handle_pointer_up();
+
+        let widget = engine.widget(id).unwrap();
+        assert_eq!(widget.position, origin);
+        assert_eq!(
+            widget.size,
+            Size::new(size.width + 50.0, size.height + 30.0)
+        );
+    }
+
+    #[test]
+    fn test_windows_cover_widgets() {
+        let mut engine = engine();
+        let origin = engine.viewport.screen_to_canvas(Vec2::new(100.0, 100.0));
+        let id = engine.add_widget(0, note(""), Some(origin)).unwrap();
+        assert_eq!(engine.widget_at(120.0, 120.0), Some(id));
+
+        engine.create_window(WindowConfig {
+            position: Some(origin),
+            size: Size::new(400.0, 400.0),
+            ..Default::default()
+        });
+        assert_eq!(engine.widget_at(120.0, 120.0), None);
+    }
+
+    #[test]
+    fn test_widgets_persist_with_desktop() {
+        let mut engine = engine();
+        let id = engine.add_widget(0, note("keep"), None).unwrap();
+        let snapshot = engine.export_snapshot();
+
+        let mut restored = DesktopEngine::new();
+        restored.init(1920.0, 1080.0);
+        restored.import_snapshot(snapshot).unwrap();
+        assert_eq!(restored.widget(id).unwrap().kind, note("keep"));
+
+        // New widgets don't reuse restored IDs
+        let new_id = restored.add_widget(0, note(""), None).unwrap();
+        assert!(new_id > id);
+    }
+}
@@ -3,25 +3,39 @@
 use super::DesktopEngine;
 use crate::desktop::DesktopId;
 use crate::math::{Camera, Rect, Size, Vec2};
-use crate::window::{WindowConfig, WindowId, WindowType};
+use crate::window::{ResizeIncrement, WindowConfig, WindowId, WindowType};
 use tracing::{debug, info, warn};
 
 impl DesktopEngine {
     /// Create a window
+    ///
+    /// Window rules are applied first and may change the size, position,
+    /// resizability and target desktop.
     pub fn create_window(&mut self, mut config: WindowConfig) -> WindowId {
+        let desktop = self.apply_window_rules(&mut config);
+        self.insert_window(desktop, config)
+    }
+
+    /// Create a window on a specific desktop, without applying window rules
+    pub(crate) fn insert_window(&mut self, desktop: usize, mut config: WindowConfig) -> WindowId {
         if config.position.is_none() {
             config.position = Some(self.calculate_cascade_position(&config));
         }
 
         let id = self.windows.create(config.clone());
+        self.desktops.add_window_to_desktop(desktop, id);
+
+        // A window opened on a background desktop must not steal focus
         let active = self.desktops.active_index();
-        self.desktops.add_window_to_desktop(active, id);
+        if desktop != active {
+            self.focus_top_window_on_desktop(active);
+        }
 
         info!(
             window_id = id,
             title = %config.title,
             app_id = %config.app_id,
-            desktop = active,
+            desktop,
             "window created"
         );
 
@@ -121,6 +135,24 @@ impl DesktopEngine {
         self.windows.resize(id, Size::new(width, height));
     }
 
+    /// Update a window's aspect-ratio lock and resize increments
+    ///
+    /// Apps call this at runtime (e.g. a terminal after a font change); the
+    /// window is re-sized to satisfy the new constraints.
+    pub fn set_window_resize_constraints(
+        &mut self,
+        id: WindowId,
+        aspect_ratio: Option<f32>,
+        increment: Option<ResizeIncrement>,
+    ) {
+        let aspect_ratio = aspect_ratio.filter(|r| r.is_finite() && *r > 0.0);
+        let increment = increment
+            .filter(|inc| inc.step.width >= 0.0 && inc.step.height >= 0.0);
+        self.windows
+            .set_resize_constraints(id, aspect_ratio, increment);
+        debug!(window_id = id, ?aspect_ratio, ?increment, "resize constraints updated");
+    }
+
     /// Minimize a window
     pub fn minimize_window(&mut self, id: WindowId) {
         self.windows.minimize(id);
@@ -144,8 +176,14 @@ impl DesktopEngine {
     }
 
     /// Restore a window
+    ///
+    /// A window minimized while fullscreen comes back fullscreen, fitted to
+    /// the current screen.
     pub fn restore_window(&mut self, id: WindowId) {
         self.windows.restore(id);
+        if self.windows.get(id).is_some_and(|w| w.is_fullscreen()) {
+            self.refit_fullscreen_windows();
+        }
     }
 
     /// Create a desktop
@@ -178,11 +216,10 @@ impl DesktopEngine {
 
         if self.desktops.switch_to(index) {
             self.focus_top_window_on_desktop(index);
-            self.crossfade = Some(crate::transition::Crossfade::switch_desktop(
+            self.start_crossfade(
+                crate::transition::Crossfade::switch_desktop(now_ms, current_index, index),
                 now_ms,
-                current_index,
-                index,
-            ));
+            );
             info!(from = current_index, to = index, "switching desktop");
         } else {
             warn!(target_index = index, "desktop switch failed - index out of bounds");
@@ -211,23 +248,29 @@ impl DesktopEngine {
 
     /// Launch an application (creates window with app_id)
     pub fn launch_app(&mut self, app_id: &str) -> WindowId {
+        let config = self.app_window_config(app_id);
+        info!(app_id = %app_id, "launching app");
+        self.create_window(config)
+    }
+
+    /// Default window configuration for an app
+    pub(crate) fn app_window_config(&self, app_id: &str) -> WindowConfig {
         let app_config = self.get_app_config(app_id);
         let (win_w, win_h) = self.calculate_app_window_size(&app_config);
 
-        let config = WindowConfig {
+        WindowConfig {
             title: app_config.title.to_string(),
             position: None,
             size: Size::new(win_w, win_h),
             min_size: Some(Size::new(app_config.min_width, app_config.min_height)),
             max_size: None,
+            aspect_ratio: None,
+            resize_increment: None,
             app_id: app_id.to_string(),
             process_id: None,
             content_interactive: app_config.content_interactive,
             window_type: app_config.window_type,
-        };
-
-        info!(app_id = %app_id, "launching app");
-        self.create_window(config)
+        }
     }
 
     /// Calculate window size based on screen dimensions and app config
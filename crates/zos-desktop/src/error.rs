@@ -4,6 +4,8 @@
 //! in the desktop crate, following the project's error handling conventions.
 
 use crate::desktop::DesktopId;
+use crate::rules::RuleId;
+use crate::widget::WidgetId;
 use crate::window::WindowId;
 
 /// Errors that can occur in desktop compositor operations
@@ -23,6 +25,15 @@ pub enum DesktopError {
         count: usize,
     },
 
+    /// Window rule with the given ID was not found
+    RuleNotFound(RuleId),
+
+    /// Widget with the given ID was not found
+    WidgetNotFound(WidgetId),
+
+    /// A hotkey string could not be parsed
+    InvalidHotkey(String),
+
     /// An operation was attempted that is not valid in the current state
     InvalidOperation {
         /// The operation that was attempted
@@ -53,6 +64,9 @@ impl std::fmt::Display for DesktopError {
                     index, count
                 )
             }
+            Self::RuleNotFound(id) => write!(f, "window rule not found: {}", id),
+            Self::WidgetNotFound(id) => write!(f, "widget not found: {}", id),
+            Self::InvalidHotkey(chord) => write!(f, "invalid hotkey: {}", chord),
             Self::InvalidOperation { op, reason } => {
                 write!(f, "invalid operation '{}': {}", op, reason)
             }
@@ -86,6 +100,15 @@ mod tests {
             "desktop index 5 out of bounds (count: 3)"
         );
 
+        let err = DesktopError::RuleNotFound(7);
+        assert_eq!(err.to_string(), "window rule not found: 7");
+
+        let err = DesktopError::WidgetNotFound(9);
+        assert_eq!(err.to_string(), "widget not found: 9");
+
+        let err = DesktopError::InvalidHotkey("Ctrl+".to_string());
+        assert_eq!(err.to_string(), "invalid hotkey: Ctrl+");
+
         let err = DesktopError::InvalidOperation {
             op: "close_window",
             reason: "window is already closed",
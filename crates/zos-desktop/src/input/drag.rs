@@ -1,6 +1,7 @@
 //! Drag state for input operations
 
 use crate::math::{Size, Vec2};
+use crate::widget::WidgetId;
 use crate::window::{WindowId, WindowRegion};
 
 /// Current drag operation state
@@ -33,6 +34,29 @@ pub enum DragState {
         /// Mouse position at start (canvas coords)
         start_mouse: Vec2,
     },
+    /// Carrying a window over desktop tiles in the void
+    CarryWindow {
+        /// Window being carried
+        window_id: WindowId,
+        /// Current pointer position (screen coords)
+        pointer: Vec2,
+    },
+    /// Moving a canvas widget
+    MoveWidget {
+        /// Widget being moved
+        widget_id: WidgetId,
+        /// Offset from widget origin to cursor
+        offset: Vec2,
+    },
+    /// Resizing a canvas widget from its bottom-right corner
+    ResizeWidget {
+        /// Widget being resized
+        widget_id: WidgetId,
+        /// Widget size at start
+        start_size: Size,
+        /// Mouse position at start (canvas coords)
+        start_mouse: Vec2,
+    },
 }
 
 impl DragState {
@@ -54,11 +78,27 @@ impl DragState {
         matches!(self, DragState::ResizeWindow { .. })
     }
 
+    /// Check if this is a window carry operation
+    #[inline]
+    pub fn is_carry(&self) -> bool {
+        matches!(self, DragState::CarryWindow { .. })
+    }
+
     /// Get the window ID if this is a window operation
     pub fn window_id(&self) -> Option<WindowId> {
         match self {
             DragState::MoveWindow { window_id, .. } => Some(*window_id),
             DragState::ResizeWindow { window_id, .. } => Some(*window_id),
+            DragState::CarryWindow { window_id, .. } => Some(*window_id),
+            _ => None,
+        }
+    }
+
+    /// Get the widget ID if this is a widget operation
+    pub fn widget_id(&self) -> Option<WidgetId> {
+        match self {
+            DragState::MoveWidget { widget_id, .. } => Some(*widget_id),
+            DragState::ResizeWidget { widget_id, .. } => Some(*widget_id),
             _ => None,
         }
     }
@@ -110,6 +150,30 @@ mod tests {
         assert_eq!(state.window_id(), Some(123));
     }
 
+    #[test]
+    fn test_carry_window_state() {
+        let state = DragState::CarryWindow {
+            window_id: 7,
+            pointer: Vec2::new(300.0, 200.0),
+        };
+
+        assert!(state.is_carry());
+        assert!(!state.is_move());
+        assert_eq!(state.window_id(), Some(7));
+    }
+
+    #[test]
+    fn test_widget_states() {
+        let state = DragState::MoveWidget {
+            widget_id: 3,
+            offset: Vec2::new(5.0, 5.0),
+        };
+
+        assert!(!state.is_move());
+        assert_eq!(state.window_id(), None);
+        assert_eq!(state.widget_id(), Some(3));
+    }
+
     #[test]
     fn test_drag_state_clone() {
         let state = DragState::MoveWindow {
@@ -1,64 +1,66 @@
 //! Input routing module
 //!
-//! Provides input state machine for drag/resize operations.
+//! Provides input state machine for drag/resize operations, and recording
+//! of raw input for replay.
 
 mod drag;
+mod recorder;
 mod result;
 mod router;
 
 pub use drag::DragState;
+pub use recorder::{InputEvent, InputRecorder, InputRecording, RecordedInput, RECORDING_VERSION};
 pub use result::InputResult;
 pub use router::InputRouter;
 
 use crate::math::{Size, Vec2};
-use crate::window::WindowRegion;
+use crate::window::{ResizeAxis, ResizeConstraints, WindowRegion};
 
 /// Calculate new position and size after resize operation
+///
+/// The edges opposite the handle stay in place, including when the size is
+/// limited by `constraints`.
 pub fn calculate_resize(
     handle: WindowRegion,
     start_pos: Vec2,
     start_size: Size,
     delta: Vec2,
+    constraints: &ResizeConstraints,
 ) -> (Vec2, Size) {
-    let mut new_pos = start_pos;
-    let mut new_size = start_size;
+    if !handle.is_resize() {
+        return (start_pos, start_size);
+    }
 
-    match handle {
-        WindowRegion::ResizeN => {
-            new_pos.y = start_pos.y + delta.y;
-            new_size.height = (start_size.height - delta.y).max(100.0);
-        }
-        WindowRegion::ResizeS => {
-            new_size.height = (start_size.height + delta.y).max(100.0);
-        }
-        WindowRegion::ResizeE => {
-            new_size.width = (start_size.width + delta.x).max(100.0);
-        }
-        WindowRegion::ResizeW => {
-            new_pos.x = start_pos.x + delta.x;
-            new_size.width = (start_size.width - delta.x).max(100.0);
-        }
-        WindowRegion::ResizeNE => {
-            new_pos.y = start_pos.y + delta.y;
-            new_size.width = (start_size.width + delta.x).max(100.0);
-            new_size.height = (start_size.height - delta.y).max(100.0);
-        }
-        WindowRegion::ResizeNW => {
-            new_pos.x = start_pos.x + delta.x;
-            new_pos.y = start_pos.y + delta.y;
-            new_size.width = (start_size.width - delta.x).max(100.0);
-            new_size.height = (start_size.height - delta.y).max(100.0);
-        }
-        WindowRegion::ResizeSE => {
-            new_size.width = (start_size.width + delta.x).max(100.0);
-            new_size.height = (start_size.height + delta.y).max(100.0);
-        }
-        WindowRegion::ResizeSW => {
-            new_pos.x = start_pos.x + delta.x;
-            new_size.width = (start_size.width - delta.x).max(100.0);
-            new_size.height = (start_size.height + delta.y).max(100.0);
-        }
-        _ => {}
+    let (west, north) = match handle {
+        WindowRegion::ResizeW | WindowRegion::ResizeSW => (true, false),
+        WindowRegion::ResizeN | WindowRegion::ResizeNE => (false, true),
+        WindowRegion::ResizeNW => (true, true),
+        _ => (false, false),
+    };
+    let horizontal = !matches!(handle, WindowRegion::ResizeN | WindowRegion::ResizeS);
+    let vertical = !matches!(handle, WindowRegion::ResizeE | WindowRegion::ResizeW);
+
+    let mut requested = start_size;
+    if horizontal {
+        requested.width += if west { -delta.x } else { delta.x };
+    }
+    if vertical {
+        requested.height += if north { -delta.y } else { delta.y };
+    }
+
+    let axis = match (horizontal, vertical) {
+        (true, false) => ResizeAxis::Width,
+        (false, true) => ResizeAxis::Height,
+        _ => ResizeAxis::dominant(start_size, requested),
+    };
+    let new_size = constraints.apply(requested, axis);
+
+    let mut new_pos = start_pos;
+    if west {
+        new_pos.x = start_pos.x + start_size.width - new_size.width;
+    }
+    if north {
+        new_pos.y = start_pos.y + start_size.height - new_size.height;
     }
 
     (new_pos, new_size)
@@ -67,6 +69,14 @@ pub fn calculate_resize(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::window::ResizeIncrement;
+
+    fn constraints() -> ResizeConstraints {
+        ResizeConstraints {
+            min_size: Size::new(100.0, 100.0),
+            ..Default::default()
+        }
+    }
 
     #[test]
     fn test_resize_south() {
@@ -75,6 +85,7 @@ mod tests {
             Vec2::new(100.0, 100.0),
             Size::new(400.0, 300.0),
             Vec2::new(0.0, 50.0),
+            &constraints(),
         );
         assert!((pos.x - 100.0).abs() < 0.001);
         assert!((pos.y - 100.0).abs() < 0.001);
@@ -89,8 +100,61 @@ mod tests {
             Vec2::new(100.0, 100.0),
             Size::new(400.0, 300.0),
             Vec2::new(0.0, -50.0),
+            &constraints(),
         );
         assert!((pos.y - 50.0).abs() < 0.001);
         assert!((size.height - 350.0).abs() < 0.001);
     }
+
+    #[test]
+    fn test_resize_west_keeps_east_edge_at_min_size() {
+        let (pos, size) = calculate_resize(
+            WindowRegion::ResizeW,
+            Vec2::new(100.0, 100.0),
+            Size::new(400.0, 300.0),
+            Vec2::new(500.0, 0.0),
+            &constraints(),
+        );
+        assert!((size.width - 100.0).abs() < 0.001);
+        assert!((pos.x + size.width - 500.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_resize_corner_with_aspect_ratio() {
+        let locked = ResizeConstraints {
+            aspect_ratio: Some(2.0),
+            ..constraints()
+        };
+        let (pos, size) = calculate_resize(
+            WindowRegion::ResizeNW,
+            Vec2::new(0.0, 0.0),
+            Size::new(400.0, 200.0),
+            Vec2::new(-200.0, -10.0),
+            &locked,
+        );
+        assert!((size.width - 600.0).abs() < 0.001);
+        assert!((size.height - 300.0).abs() < 0.001);
+        // Bottom-right corner stays put
+        assert!((pos.x + size.width - 400.0).abs() < 0.001);
+        assert!((pos.y + size.height - 200.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_resize_edge_snaps_to_increment() {
+        let cells = ResizeConstraints {
+            increment: Some(ResizeIncrement {
+                step: Size::new(10.0, 20.0),
+                base: Size::ZERO,
+            }),
+            ..constraints()
+        };
+        let (_, size) = calculate_resize(
+            WindowRegion::ResizeSE,
+            Vec2::new(0.0, 0.0),
+            Size::new(400.0, 300.0),
+            Vec2::new(37.0, 29.0),
+            &cells,
+        );
+        assert_eq!(size, Size::new(430.0, 320.0));
+    }
 }
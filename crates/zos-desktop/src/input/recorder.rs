@@ -0,0 +1,246 @@
+//! Input recording
+//!
+//! Captures pointer and wheel events with their time offsets so a gesture
+//! can be saved (as JSON, typically to a file in the VFS) and replayed later
+//! as a script on the virtual clock. Replays go through the same
+//! `handle_pointer_*` paths as live input, so a recorded bug reproduces
+//! exactly.
+
+use crate::error::{DesktopError, DesktopResult};
+use crate::math::Size;
+use crate::script::ScriptOp;
+use crate::window::WindowId;
+use serde::{Deserialize, Serialize};
+
+/// Current recording format version
+pub const RECORDING_VERSION: u32 = 1;
+
+/// A raw input event, as passed to the engine's pointer handlers
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum InputEvent {
+    /// Pointer pressed (button 0 = left, 1 = middle)
+    PointerDown {
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        button: u8,
+        #[serde(default)]
+        ctrl: bool,
+        #[serde(default)]
+        shift: bool,
+    },
+    /// Pointer moved
+    PointerMove { x: f32, y: f32 },
+    /// Pointer released
+    PointerUp,
+    /// Wheel scrolled (with Ctrl: zoom)
+    Wheel {
+        dx: f32,
+        dy: f32,
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        ctrl: bool,
+    },
+    /// Window move started from the frontend (title bar)
+    StartWindowDrag { window: WindowId, x: f32, y: f32 },
+    /// Window resize started from the frontend (`direction` is "n", "se", ...)
+    StartWindowResize {
+        window: WindowId,
+        direction: String,
+        x: f32,
+        y: f32,
+    },
+    /// Window carry started in the void
+    StartWindowCarry { window: WindowId, x: f32, y: f32 },
+}
+
+impl InputEvent {
+    /// The script step that replays this event
+    pub fn to_script_op(&self) -> ScriptOp {
+        match self.clone() {
+            InputEvent::PointerDown {
+                x,
+                y,
+                button,
+                ctrl,
+                shift,
+            } => ScriptOp::PointerDown {
+                x,
+                y,
+                button,
+                ctrl,
+                shift,
+            },
+            InputEvent::PointerMove { x, y } => ScriptOp::PointerMove { x, y },
+            InputEvent::PointerUp => ScriptOp::PointerUp,
+            InputEvent::Wheel { dx, dy, x, y, ctrl } => ScriptOp::Wheel { dx, dy, x, y, ctrl },
+            InputEvent::StartWindowDrag { window, x, y } => {
+                ScriptOp::StartWindowDrag { window, x, y }
+            }
+            InputEvent::StartWindowResize {
+                window,
+                direction,
+                x,
+                y,
+            } => ScriptOp::StartWindowResize {
+                window,
+                direction,
+                x,
+                y,
+            },
+            InputEvent::StartWindowCarry { window, x, y } => {
+                ScriptOp::StartWindowCarry { window, x, y }
+            }
+        }
+    }
+}
+
+/// An event and when it happened, relative to the start of the recording
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedInput {
+    pub at_ms: f64,
+    #[serde(flatten)]
+    pub event: InputEvent,
+}
+
+/// A finished recording
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    /// Format version ([`RECORDING_VERSION`])
+    pub version: u32,
+    /// Screen size during recording (coordinates are screen pixels)
+    pub screen_width: f32,
+    pub screen_height: f32,
+    /// Events in the order they happened
+    pub events: Vec<RecordedInput>,
+}
+
+impl InputRecording {
+    /// Check a loaded recording is in the current format
+    pub fn validated(self) -> DesktopResult<Self> {
+        if self.version != RECORDING_VERSION {
+            return Err(DesktopError::SerializationError(format!(
+                "unsupported recording version {}",
+                self.version
+            )));
+        }
+        Ok(self)
+    }
+
+    /// Total length of the recording
+    pub fn duration_ms(&self) -> f64 {
+        self.events.last().map_or(0.0, |e| e.at_ms)
+    }
+
+    /// The recording as a script: each event, preceded by an `advance` for
+    /// the time since the previous one
+    pub fn to_script(&self) -> Vec<ScriptOp> {
+        let mut ops = Vec::with_capacity(self.events.len() * 2);
+        let mut last_ms = 0.0;
+        for recorded in &self.events {
+            let gap = recorded.at_ms - last_ms;
+            if gap > 0.0 {
+                ops.push(ScriptOp::Advance { ms: gap });
+                last_ms = recorded.at_ms;
+            }
+            ops.push(recorded.event.to_script_op());
+        }
+        ops
+    }
+}
+
+/// Collects events while recording is on
+#[derive(Clone, Debug)]
+pub struct InputRecorder {
+    start_ms: f64,
+    events: Vec<RecordedInput>,
+}
+
+impl InputRecorder {
+    /// Start recording at `now_ms`
+    pub fn new(now_ms: f64) -> Self {
+        Self {
+            start_ms: now_ms,
+            events: Vec::new(),
+        }
+    }
+
+    /// Add an event (clock going backwards is treated as no time passing)
+    pub fn record(&mut self, event: InputEvent, now_ms: f64) {
+        let last = self.events.last().map_or(0.0, |e| e.at_ms);
+        let at_ms = (now_ms - self.start_ms).max(last);
+        self.events.push(RecordedInput { at_ms, event });
+    }
+
+    /// Number of events so far
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether nothing has been recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Finish the recording
+    pub fn finish(self, screen: Size) -> InputRecording {
+        InputRecording {
+            version: RECORDING_VERSION,
+            screen_width: screen.width,
+            screen_height: screen.height,
+            events: self.events,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_to_script() {
+        let mut recorder = InputRecorder::new(1_000.0);
+        recorder.record(InputEvent::PointerMove { x: 1.0, y: 2.0 }, 1_000.0);
+        recorder.record(InputEvent::PointerUp, 1_050.0);
+        // Clock skew doesn't reorder events
+        recorder.record(InputEvent::PointerUp, 1_040.0);
+        let recording = recorder.finish(Size::new(800.0, 600.0));
+
+        assert_eq!(recording.duration_ms(), 50.0);
+        assert_eq!(
+            recording.to_script(),
+            vec![
+                ScriptOp::PointerMove { x: 1.0, y: 2.0 },
+                ScriptOp::Advance { ms: 50.0 },
+                ScriptOp::PointerUp,
+                ScriptOp::PointerUp,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recording_json() {
+        let mut recorder = InputRecorder::new(0.0);
+        recorder.record(
+            InputEvent::PointerDown {
+                x: 10.0,
+                y: 20.0,
+                button: 0,
+                ctrl: false,
+                shift: true,
+            },
+            5.0,
+        );
+        let recording = recorder.finish(Size::new(800.0, 600.0));
+        let json = serde_json::to_string(&recording).unwrap();
+        assert!(json.contains(r#"{"at_ms":5.0,"event":"pointer_down","x":10.0"#));
+        let parsed: InputRecording = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.validated().unwrap(), recording);
+
+        let future = json.replace(r#""version":1"#, r#""version":2"#);
+        let parsed: InputRecording = serde_json::from_str(&future).unwrap();
+        assert!(parsed.validated().is_err());
+    }
+}
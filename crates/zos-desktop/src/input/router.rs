@@ -2,6 +2,7 @@
 
 use super::DragState;
 use crate::math::{Size, Vec2};
+use crate::widget::WidgetId;
 use crate::window::{WindowId, WindowRegion};
 
 /// Input router managing drag state
@@ -65,6 +66,37 @@ impl InputRouter {
         });
     }
 
+    /// Start carrying a window in the void
+    pub fn start_window_carry(&mut self, window_id: WindowId, pointer: Vec2) {
+        self.drag = Some(DragState::CarryWindow { window_id, pointer });
+    }
+
+    /// Update the pointer position of a window carry
+    pub fn update_carry_pointer(&mut self, position: Vec2) {
+        if let Some(DragState::CarryWindow { pointer, .. }) = &mut self.drag {
+            *pointer = position;
+        }
+    }
+
+    /// Start widget move operation
+    pub fn start_widget_move(&mut self, widget_id: WidgetId, offset: Vec2) {
+        self.drag = Some(DragState::MoveWidget { widget_id, offset });
+    }
+
+    /// Start widget resize operation
+    pub fn start_widget_resize(
+        &mut self,
+        widget_id: WidgetId,
+        start_size: Size,
+        start_mouse: Vec2,
+    ) {
+        self.drag = Some(DragState::ResizeWidget {
+            widget_id,
+            start_size,
+            start_mouse,
+        });
+    }
+
     /// End current drag operation
     pub fn end_drag(&mut self) {
         self.drag = None;
@@ -0,0 +1,166 @@
+//! Keyboard commands
+
+use crate::math::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Direction for spatial keyboard commands
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    /// Unit vector in canvas coordinates (y grows downward)
+    pub fn unit(self) -> Vec2 {
+        match self {
+            Direction::Left => Vec2::new(-1.0, 0.0),
+            Direction::Right => Vec2::new(1.0, 0.0),
+            Direction::Up => Vec2::new(0.0, -1.0),
+            Direction::Down => Vec2::new(0.0, 1.0),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Direction::Left => "left",
+            Direction::Right => "right",
+            Direction::Up => "up",
+            Direction::Down => "down",
+        }
+    }
+}
+
+/// Something a hotkey does
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum KeyCommand {
+    /// Focus the nearest window in a direction
+    Focus { direction: Direction },
+    /// Move the focused window by one step
+    MoveWindow { direction: Direction },
+    /// Grow (right/down) or shrink (left/up) the focused window by one step
+    ResizeWindow { direction: Direction },
+    /// Switch to the previous desktop (wrapping)
+    PreviousDesktop,
+    /// Switch to the next desktop (wrapping)
+    NextDesktop,
+    /// Enter the void, or leave it to the active desktop
+    ToggleVoid,
+    /// Fit every window of the active desktop on screen
+    ZoomToFit,
+    /// Fit the focused window on screen
+    ZoomToFocused,
+    /// Toggle fullscreen for the focused window
+    ToggleFullscreen,
+    /// Leave fullscreen
+    ExitFullscreen,
+    /// Open a terminal (performed by the frontend)
+    LaunchTerminal,
+    /// Close the focused window and its process (performed by the frontend)
+    CloseWindow,
+    /// Open the command palette (performed by the frontend)
+    OpenCommandPalette,
+}
+
+impl KeyCommand {
+    /// Whether the frontend carries this command out (it needs the supervisor)
+    pub fn is_frontend(self) -> bool {
+        matches!(
+            self,
+            KeyCommand::LaunchTerminal | KeyCommand::CloseWindow | KeyCommand::OpenCommandPalette
+        )
+    }
+
+    /// Whether the command still applies while a window is fullscreen
+    ///
+    /// Every other key belongs to the fullscreen app.
+    pub fn allowed_in_fullscreen(self) -> bool {
+        matches!(
+            self,
+            KeyCommand::ToggleFullscreen | KeyCommand::ExitFullscreen | KeyCommand::ToggleVoid
+        )
+    }
+
+    /// Cheat-sheet section
+    pub fn category(self) -> &'static str {
+        match self {
+            KeyCommand::Focus { .. }
+            | KeyCommand::MoveWindow { .. }
+            | KeyCommand::ResizeWindow { .. }
+            | KeyCommand::ToggleFullscreen
+            | KeyCommand::ExitFullscreen
+            | KeyCommand::CloseWindow => "Windows",
+            KeyCommand::PreviousDesktop | KeyCommand::NextDesktop | KeyCommand::ToggleVoid => {
+                "Desktops"
+            }
+            KeyCommand::ZoomToFit | KeyCommand::ZoomToFocused => "View",
+            KeyCommand::LaunchTerminal | KeyCommand::OpenCommandPalette => "Apps",
+        }
+    }
+
+    /// Cheat-sheet description
+    pub fn description(self) -> String {
+        match self {
+            KeyCommand::Focus { direction } => format!("Focus window {}", direction.name()),
+            KeyCommand::MoveWindow { direction } => format!("Move window {}", direction.name()),
+            KeyCommand::ResizeWindow { direction } => match direction {
+                Direction::Left => "Make window narrower".to_string(),
+                Direction::Right => "Make window wider".to_string(),
+                Direction::Up => "Make window shorter".to_string(),
+                Direction::Down => "Make window taller".to_string(),
+            },
+            KeyCommand::PreviousDesktop => "Previous desktop".to_string(),
+            KeyCommand::NextDesktop => "Next desktop".to_string(),
+            KeyCommand::ToggleVoid => "Enter or leave the void".to_string(),
+            KeyCommand::ZoomToFit => "Fit all windows on screen".to_string(),
+            KeyCommand::ZoomToFocused => "Fit focused window on screen".to_string(),
+            KeyCommand::ToggleFullscreen => "Toggle fullscreen".to_string(),
+            KeyCommand::ExitFullscreen => "Leave fullscreen".to_string(),
+            KeyCommand::LaunchTerminal => "New terminal".to_string(),
+            KeyCommand::CloseWindow => "Close focused window".to_string(),
+            KeyCommand::OpenCommandPalette => "Open command palette".to_string(),
+        }
+    }
+}
+
+/// Result of handling a key press
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum KeyResult {
+    /// The engine carried out a command
+    Handled,
+    /// No hotkey (or not applicable now): let the key through
+    Unhandled,
+    /// A command for the frontend to carry out
+    Frontend(KeyCommand),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_json() {
+        let command = KeyCommand::MoveWindow {
+            direction: Direction::Left,
+        };
+        let json = serde_json::to_string(&command).unwrap();
+        assert_eq!(json, r#"{"command":"move_window","direction":"left"}"#);
+        assert_eq!(serde_json::from_str::<KeyCommand>(&json).unwrap(), command);
+
+        let result = KeyResult::Frontend(KeyCommand::LaunchTerminal);
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"type":"frontend","command":"launch_terminal"}"#
+        );
+        let result = KeyResult::Frontend(KeyCommand::OpenCommandPalette);
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"type":"frontend","command":"open_command_palette"}"#
+        );
+    }
+}
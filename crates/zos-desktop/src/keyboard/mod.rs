@@ -0,0 +1,23 @@
+//! Keyboard window management
+//!
+//! Everything the pointer can do to windows and desktops is also reachable
+//! from the keyboard: directional focus, moving and resizing the focused
+//! window in steps, switching desktops and entering/leaving the void. Keys
+//! map to [`KeyCommand`]s through a [`HotkeyRegistry`], which also produces
+//! the cheat sheet shown to users.
+//!
+//! The engine carries out most commands itself. A few (opening a terminal,
+//! closing a window together with its process) need the supervisor and are
+//! handed back to the frontend as [`KeyResult::Frontend`].
+
+mod command;
+mod registry;
+
+pub use command::{Direction, KeyCommand, KeyResult};
+pub use registry::{CheatSheetEntry, Hotkey, HotkeyRegistry, KeyChord};
+
+/// Distance a window moves per key press (canvas pixels)
+pub const MOVE_STEP: f32 = 40.0;
+
+/// Size change per key press (canvas pixels)
+pub const RESIZE_STEP: f32 = 40.0;
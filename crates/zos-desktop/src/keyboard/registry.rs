@@ -0,0 +1,328 @@
+//! Hotkey registry
+//!
+//! Maps key chords to commands. Chords are written the way the cheat sheet
+//! shows them, `Ctrl+Alt+Shift+Meta+Key`, where `Key` is a
+//! `KeyboardEvent.key` value (`ArrowLeft`, `F3`, `` ` ``, `t`). Single
+//! characters match case-insensitively so Caps Lock doesn't change a binding.
+
+use super::command::{Direction, KeyCommand};
+use crate::error::{DesktopError, DesktopResult};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Cheat-sheet section order
+const CATEGORIES: [&str; 4] = ["Windows", "Desktops", "View", "Apps"];
+
+/// A key plus modifiers
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyChord {
+    key: String,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    meta: bool,
+}
+
+impl KeyChord {
+    /// Chord from a key event
+    pub fn new(key: &str, ctrl: bool, shift: bool, alt: bool, meta: bool) -> Self {
+        let key = if key.chars().count() == 1 {
+            key.to_lowercase()
+        } else {
+            key.to_string()
+        };
+        Self {
+            key,
+            ctrl,
+            alt,
+            shift,
+            meta,
+        }
+    }
+
+    /// Parse a chord such as `Ctrl+Shift+ArrowLeft`
+    pub fn parse(chord: &str) -> DesktopResult<Self> {
+        let invalid = || DesktopError::InvalidHotkey(chord.to_string());
+        let (modifiers, key) = match chord.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None => chord.rsplit_once('+').unwrap_or(("", chord)),
+        };
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(invalid());
+        }
+
+        let (mut ctrl, mut shift, mut alt, mut meta) = (false, false, false, false);
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            let flag = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut ctrl,
+                "shift" => &mut shift,
+                "alt" | "option" => &mut alt,
+                "meta" | "cmd" | "super" => &mut meta,
+                _ => return Err(invalid()),
+            };
+            *flag = true;
+        }
+        Ok(Self::new(key, ctrl, shift, alt, meta))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (on, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.alt, "Alt+"),
+            (self.shift, "Shift+"),
+            (self.meta, "Meta+"),
+        ] {
+            if on {
+                f.write_str(name)?;
+            }
+        }
+        if self.key.chars().count() == 1 {
+            write!(f, "{}", self.key.to_uppercase())
+        } else {
+            f.write_str(&self.key)
+        }
+    }
+}
+
+impl TryFrom<String> for KeyChord {
+    type Error = DesktopError;
+
+    fn try_from(chord: String) -> DesktopResult<Self> {
+        Self::parse(&chord)
+    }
+}
+
+impl From<KeyChord> for String {
+    fn from(chord: KeyChord) -> Self {
+        chord.to_string()
+    }
+}
+
+/// A chord bound to a command
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hotkey {
+    pub keys: KeyChord,
+    pub command: KeyCommand,
+}
+
+/// One cheat-sheet row: a command and every chord bound to it
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CheatSheetEntry {
+    pub category: &'static str,
+    pub description: String,
+    #[serde(flatten)]
+    pub command: KeyCommand,
+    pub keys: Vec<String>,
+}
+
+/// Hotkey bindings, in the order they were added
+#[derive(Clone, Debug, PartialEq)]
+pub struct HotkeyRegistry {
+    bindings: Vec<Hotkey>,
+}
+
+impl Default for HotkeyRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        for (chord, command) in default_bindings() {
+            if let Ok(keys) = KeyChord::parse(chord) {
+                registry.bind(keys, command);
+            }
+        }
+        registry
+    }
+}
+
+impl HotkeyRegistry {
+    /// Registry with no bindings
+    pub fn empty() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Command bound to a chord
+    pub fn lookup(&self, keys: &KeyChord) -> Option<KeyCommand> {
+        self.bindings
+            .iter()
+            .find(|h| &h.keys == keys)
+            .map(|h| h.command)
+    }
+
+    /// Bind a chord, replacing its previous command
+    pub fn bind(&mut self, keys: KeyChord, command: KeyCommand) {
+        match self.bindings.iter_mut().find(|h| h.keys == keys) {
+            Some(hotkey) => hotkey.command = command,
+            None => self.bindings.push(Hotkey { keys, command }),
+        }
+    }
+
+    /// Remove a chord's binding
+    pub fn unbind(&mut self, keys: &KeyChord) -> bool {
+        let before = self.bindings.len();
+        self.bindings.retain(|h| &h.keys != keys);
+        self.bindings.len() != before
+    }
+
+    /// All bindings
+    pub fn bindings(&self) -> &[Hotkey] {
+        &self.bindings
+    }
+
+    /// Bindings grouped by command and section, for a help overlay
+    pub fn cheat_sheet(&self) -> Vec<CheatSheetEntry> {
+        let mut entries: Vec<CheatSheetEntry> = Vec::new();
+        for hotkey in &self.bindings {
+            match entries.iter_mut().find(|e| e.command == hotkey.command) {
+                Some(entry) => entry.keys.push(hotkey.keys.to_string()),
+                None => entries.push(CheatSheetEntry {
+                    category: hotkey.command.category(),
+                    description: hotkey.command.description(),
+                    command: hotkey.command,
+                    keys: vec![hotkey.keys.to_string()],
+                }),
+            }
+        }
+        entries.sort_by_key(|e| CATEGORIES.iter().position(|c| *c == e.category));
+        entries
+    }
+}
+
+/// Built-in bindings
+fn default_bindings() -> Vec<(&'static str, KeyCommand)> {
+    let mut bindings = Vec::new();
+    for (key, direction) in [
+        ("ArrowLeft", Direction::Left),
+        ("ArrowRight", Direction::Right),
+        ("ArrowUp", Direction::Up),
+        ("ArrowDown", Direction::Down),
+    ] {
+        bindings.push((key, KeyCommand::Focus { direction }));
+    }
+    for (key, direction) in [
+        ("Alt+ArrowLeft", Direction::Left),
+        ("Alt+ArrowRight", Direction::Right),
+        ("Alt+ArrowUp", Direction::Up),
+        ("Alt+ArrowDown", Direction::Down),
+    ] {
+        bindings.push((key, KeyCommand::MoveWindow { direction }));
+    }
+    for (key, direction) in [
+        ("Alt+Shift+ArrowLeft", Direction::Left),
+        ("Alt+Shift+ArrowRight", Direction::Right),
+        ("Alt+Shift+ArrowUp", Direction::Up),
+        ("Alt+Shift+ArrowDown", Direction::Down),
+    ] {
+        bindings.push((key, KeyCommand::ResizeWindow { direction }));
+    }
+    bindings.extend([
+        ("F11", KeyCommand::ToggleFullscreen),
+        ("Escape", KeyCommand::ExitFullscreen),
+        ("c", KeyCommand::CloseWindow),
+        ("Ctrl+ArrowLeft", KeyCommand::PreviousDesktop),
+        ("Ctrl+ArrowRight", KeyCommand::NextDesktop),
+        ("Ctrl+`", KeyCommand::ToggleVoid),
+        ("F3", KeyCommand::ToggleVoid),
+        ("Home", KeyCommand::ZoomToFit),
+        ("Shift+Home", KeyCommand::ZoomToFocused),
+        ("t", KeyCommand::LaunchTerminal),
+        ("p", KeyCommand::OpenCommandPalette),
+    ]);
+    bindings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let chord = KeyChord::parse("shift+ctrl+ArrowLeft").unwrap();
+        assert_eq!(chord, KeyChord::new("ArrowLeft", true, true, false, false));
+        assert_eq!(chord.to_string(), "Ctrl+Shift+ArrowLeft");
+
+        assert_eq!(KeyChord::parse("Ctrl++").unwrap().to_string(), "Ctrl++");
+        assert_eq!(KeyChord::parse("T").unwrap(), KeyChord::parse("t").unwrap());
+        assert_eq!(KeyChord::parse("t").unwrap().to_string(), "T");
+
+        for bad in ["", "Ctrl+", "Hyper+x", "Ctrl+Page Up"] {
+            assert!(KeyChord::parse(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_defaults_lookup() {
+        let registry = HotkeyRegistry::default();
+        let lookup =
+            |key, ctrl, shift, alt| registry.lookup(&KeyChord::new(key, ctrl, shift, alt, false));
+
+        assert_eq!(
+            lookup("`", true, false, false),
+            Some(KeyCommand::ToggleVoid)
+        );
+        assert_eq!(
+            lookup("T", false, false, false),
+            Some(KeyCommand::LaunchTerminal)
+        );
+        assert_eq!(
+            lookup("ArrowUp", false, true, true),
+            Some(KeyCommand::ResizeWindow {
+                direction: Direction::Up
+            })
+        );
+        assert_eq!(lookup("ArrowUp", true, true, true), None);
+    }
+
+    #[test]
+    fn test_bind_replaces_and_unbind() {
+        let mut registry = HotkeyRegistry::default();
+        let f3 = KeyChord::parse("F3").unwrap();
+        registry.bind(f3.clone(), KeyCommand::ZoomToFit);
+        assert_eq!(registry.lookup(&f3), Some(KeyCommand::ZoomToFit));
+        assert_eq!(
+            registry.bindings().len(),
+            HotkeyRegistry::default().bindings().len()
+        );
+
+        assert!(registry.unbind(&f3));
+        assert!(!registry.unbind(&f3));
+        assert_eq!(registry.lookup(&f3), None);
+    }
+
+    #[test]
+    fn test_cheat_sheet_groups_keys() {
+        let sheet = HotkeyRegistry::default().cheat_sheet();
+        let void = sheet
+            .iter()
+            .find(|e| e.command == KeyCommand::ToggleVoid)
+            .unwrap();
+        assert_eq!(void.keys, ["Ctrl+`", "F3"]);
+        assert_eq!(void.category, "Desktops");
+
+        // Sections stay together, in order
+        let categories: Vec<_> = sheet.iter().map(|e| e.category).collect();
+        let mut sorted = categories.clone();
+        sorted.dedup();
+        assert_eq!(sorted, CATEGORIES);
+
+        let json = serde_json::to_value(&sheet[0]).unwrap();
+        assert_eq!(json["command"], "focus");
+        assert_eq!(json["direction"], "left");
+        assert_eq!(json["keys"][0], "ArrowLeft");
+    }
+
+    #[test]
+    fn test_hotkey_json_round_trip() {
+        let hotkey: Hotkey =
+            serde_json::from_str(r#"{"keys":"alt+j","command":{"command":"next_desktop"}}"#)
+                .unwrap();
+        assert_eq!(hotkey.keys.to_string(), "Alt+J");
+        assert!(serde_json::from_str::<Hotkey>(
+            r#"{"keys":"","command":{"command":"next_desktop"}}"#
+        )
+        .is_err());
+    }
+}
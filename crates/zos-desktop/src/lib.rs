@@ -14,9 +14,15 @@
 //! - [`math`]: Core geometry types (`Vec2`, `Rect`, `Size`, `Camera`)
 //! - [`window`]: Window lifecycle and management
 //! - [`desktop`]: Desktop (workspace) management
+//! - [`annotation`]: Pen strokes drawn on a desktop's canvas
+//! - [`widget`]: Lightweight canvas widgets (sticky notes, clocks)
 //! - [`input`]: Input routing and drag state machine
 //! - [`transition`]: Animation and transition systems
+//! - [`accessibility`]: High contrast, color filters and reduced motion
+//! - [`keyboard`]: Hotkeys and keyboard-only window management
 //! - [`persistence`]: State serialization for storage
+//! - [`rules`]: Declarative window rules applied at creation time
+//! - [`script`]: Deterministic scripted automation for end-to-end tests
 //! - [`error`]: Error types for fallible operations
 //!
 //! ## Example
@@ -43,13 +49,19 @@
 //! 3. **Small Modules**: Each file stays under 300 lines for maintainability
 //! 4. **Minimal Dependencies**: Core types have no browser dependencies
 
+pub mod accessibility;
+pub mod annotation;
 pub mod desktop;
 pub mod error;
 pub mod input;
+pub mod keyboard;
 pub mod math;
 pub mod persistence;
+pub mod rules;
+pub mod script;
 pub mod transition;
 pub mod types;
+pub mod widget;
 pub mod window;
 
 mod engine;
@@ -66,17 +78,25 @@ pub use wasm::*;
 pub mod background;
 
 // Re-export core types for convenience
+pub use accessibility::{ColorFilter, DisplaySettings};
+pub use annotation::{AnnotationLayer, AnnotationTool, Stroke, StrokeId, StrokePoint};
 pub use desktop::{Desktop, DesktopId, DesktopManager, PersistedDesktop, ViewMode, VoidState};
 pub use error::{DesktopError, DesktopResult};
-pub use input::{DragState, InputResult, InputRouter};
+pub use input::{DragState, InputEvent, InputRecording, InputResult, InputRouter};
+pub use keyboard::{Direction, HotkeyRegistry, KeyChord, KeyCommand, KeyResult};
 pub use math::{Camera, FrameStyle, Rect, Size, Vec2, FRAME_STYLE};
 pub use persistence::Snapshot;
+pub use rules::{WindowRule, WindowRules};
+pub use script::{ScriptOp, ScriptReport, ScriptRunner};
 pub use transition::{CameraAnimation, Crossfade, CrossfadeDirection};
+pub use widget::{Widget, WidgetId, WidgetKind, WidgetLayer};
 pub use window::{
     Window, WindowConfig, WindowId, WindowManager, WindowRegion, WindowState, WindowType,
 };
 
-pub use engine::{DesktopEngine, WindowScreenRect};
+pub use engine::{
+    DesktopEngine, FocusPolicy, MinimapLayout, MinimapWindow, WidgetScreenRect, WindowScreenRect,
+};
 pub use viewport::Viewport;
 
 /// Duration of crossfade transitions in milliseconds
@@ -58,6 +58,27 @@ impl Camera {
         )
     }
 
+    /// Camera showing all of `rect`, with `padding` screen pixels around it
+    ///
+    /// The zoom is clamped to `[min_zoom, max_zoom]`, so very small rects
+    /// are not blown up past `max_zoom`.
+    pub fn fit_rect(
+        rect: Rect,
+        screen_size: Size,
+        padding: f32,
+        min_zoom: f32,
+        max_zoom: f32,
+    ) -> Self {
+        let avail_w = (screen_size.width - padding * 2.0).max(1.0);
+        let avail_h = (screen_size.height - padding * 2.0).max(1.0);
+        let zoom_w = avail_w / rect.width.max(1.0);
+        let zoom_h = avail_h / rect.height.max(1.0);
+        Self {
+            center: rect.center(),
+            zoom: zoom_w.min(zoom_h).clamp(min_zoom, max_zoom),
+        }
+    }
+
     /// Pan the camera by a screen-space delta
     #[inline]
     pub fn pan(&mut self, dx: f32, dy: f32) {
@@ -168,4 +189,22 @@ mod tests {
         assert!((rect.width - 1920.0).abs() < 0.001);
         assert!((rect.height - 1080.0).abs() < 0.001);
     }
+
+    #[test]
+    fn test_camera_fit_rect() {
+        let screen_size = Size::new(1000.0, 500.0);
+        let rect = Rect::new(0.0, 0.0, 1800.0, 400.0);
+
+        // Width-limited: (1000 - 2 * 50) / 1800 = 0.5
+        let camera = Camera::fit_rect(rect, screen_size, 50.0, 0.1, 1.0);
+        assert_eq!(camera.center, Vec2::new(900.0, 200.0));
+        assert!((camera.zoom - 0.5).abs() < 0.001);
+
+        // Small rects are capped at max_zoom
+        let small = Rect::new(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(
+            Camera::fit_rect(small, screen_size, 50.0, 0.1, 1.0).zoom,
+            1.0
+        );
+    }
 }
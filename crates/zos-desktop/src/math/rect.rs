@@ -113,6 +113,15 @@ impl Rect {
         Some(Rect::new(x, y, right - x, bottom - y))
     }
 
+    /// Smallest rectangle containing both rectangles
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        Rect::new(x, y, right - x, bottom - y)
+    }
+
     /// Expand rectangle by amount on all sides
     #[inline]
     pub fn expand(&self, amount: f32) -> Rect {
@@ -185,6 +194,13 @@ mod tests {
         assert!((i.height - 50.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_rect_union() {
+        let a = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let b = Rect::new(150.0, -50.0, 50.0, 50.0);
+        assert_eq!(a.union(&b), Rect::new(0.0, -50.0, 200.0, 150.0));
+    }
+
     #[test]
     fn test_rect_expand() {
         let r = Rect::new(10.0, 20.0, 100.0, 50.0);
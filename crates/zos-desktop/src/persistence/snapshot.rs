@@ -42,7 +42,9 @@ impl Snapshot {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::annotation::AnnotationLayer;
     use crate::math::{Camera, Vec2};
+    use crate::widget::WidgetLayer;
 
     #[test]
     fn test_snapshot_creation() {
@@ -51,6 +53,9 @@ mod tests {
             name: "Main".to_string(),
             camera: Camera::new(),
             background: "grain".to_string(),
+            folders: Vec::new(),
+            annotations: AnnotationLayer::new(),
+            widgets: WidgetLayer::new(),
         }];
         let snapshot = Snapshot::new(0, desktops);
 
@@ -66,6 +71,9 @@ mod tests {
             name: "Main".to_string(),
             camera: Camera::at(Vec2::new(100.0, 200.0), 1.5),
             background: "grain".to_string(),
+            folders: Vec::new(),
+            annotations: AnnotationLayer::new(),
+            widgets: WidgetLayer::new(),
         }];
         let snapshot = Snapshot::new(0, desktops);
 
@@ -106,18 +114,27 @@ mod tests {
                 name: "Main".to_string(),
                 camera: Camera::at(Vec2::new(0.0, 0.0), 1.0),
                 background: "grain".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
             },
             PersistedDesktop {
                 id: 2,
                 name: "Work".to_string(),
                 camera: Camera::at(Vec2::new(2000.0, 0.0), 1.2),
                 background: "mist".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
             },
             PersistedDesktop {
                 id: 3,
                 name: "Gaming".to_string(),
                 camera: Camera::at(Vec2::new(4000.0, 0.0), 0.8),
                 background: "grain".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
             },
         ];
         let snapshot = Snapshot::new(1, desktops);
@@ -142,6 +159,9 @@ mod tests {
             name: "Test".to_string(),
             camera: Camera::at(Vec2::new(-500.0, 300.0), 2.5),
             background: "mist".to_string(),
+            folders: Vec::new(),
+            annotations: AnnotationLayer::new(),
+            widgets: WidgetLayer::new(),
         }];
         let snapshot = Snapshot::new(0, desktops);
 
@@ -162,12 +182,18 @@ mod tests {
                 name: "Grain Desktop".to_string(),
                 camera: Camera::new(),
                 background: "grain".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
             },
             PersistedDesktop {
                 id: 2,
                 name: "Mist Desktop".to_string(),
                 camera: Camera::new(),
                 background: "mist".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
             },
         ];
         let snapshot = Snapshot::new(0, desktops);
@@ -199,6 +225,9 @@ mod tests {
                 name: "Old".to_string(),
                 camera: Camera::new(),
                 background: "grain".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
             }],
         };
 
@@ -220,6 +249,9 @@ mod tests {
             name: "Main".to_string(),
             camera: Camera::at(Vec2::new(100.0, 200.0), 1.5),
             background: "grain".to_string(),
+            folders: Vec::new(),
+            annotations: AnnotationLayer::new(),
+            widgets: WidgetLayer::new(),
         }];
         let snapshot = Snapshot::new(0, desktops);
         let cloned = snapshot.clone();
@@ -238,18 +270,27 @@ mod tests {
                 name: "Desktop 1".to_string(),
                 camera: Camera::at(Vec2::new(0.0, 0.0), 1.0),
                 background: "grain".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
             },
             PersistedDesktop {
                 id: 2,
                 name: "Desktop 2".to_string(),
                 camera: Camera::at(Vec2::new(2020.0, 0.0), 1.5),
                 background: "mist".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
             },
             PersistedDesktop {
                 id: 3,
                 name: "Desktop 3".to_string(),
                 camera: Camera::at(Vec2::new(4040.0, 0.0), 0.75),
                 background: "grain".to_string(),
+                folders: Vec::new(),
+                annotations: AnnotationLayer::new(),
+                widgets: WidgetLayer::new(),
             },
         ];
         let original = Snapshot::new(1, desktops);
@@ -282,6 +323,9 @@ mod tests {
             name: "Test".to_string(),
             camera: Camera::new(),
             background: "grain".to_string(),
+            folders: Vec::new(),
+            annotations: AnnotationLayer::new(),
+            widgets: WidgetLayer::new(),
         }];
         let snapshot = Snapshot::new(0, desktops);
 
@@ -303,6 +347,9 @@ mod tests {
             name: "Work & Play \"Special\" <Test>".to_string(),
             camera: Camera::new(),
             background: "grain".to_string(),
+            folders: Vec::new(),
+            annotations: AnnotationLayer::new(),
+            widgets: WidgetLayer::new(),
         }];
         let snapshot = Snapshot::new(0, desktops);
 
@@ -319,6 +366,9 @@ mod tests {
             name: "工作桌面 🖥️".to_string(),
             camera: Camera::new(),
             background: "grain".to_string(),
+            folders: Vec::new(),
+            annotations: AnnotationLayer::new(),
+            widgets: WidgetLayer::new(),
         }];
         let snapshot = Snapshot::new(0, desktops);
 
@@ -0,0 +1,284 @@
+//! Window rules applied when windows are created
+//!
+//! A rule pairs a [`RuleMatch`] (which windows it applies to) with
+//! [`RuleActions`] (what to do with them), for example:
+//!
+//! - terminal windows open on desktop 2 at 50% width
+//! - settings always opens centered and non-resizable
+//!
+//! Rules are evaluated in list order by `DesktopEngine::create_window`. Every
+//! enabled rule that matches contributes its actions; when two rules set the
+//! same action the later one wins, so users put general rules first and
+//! specific overrides after them.
+//!
+//! The whole rule list serializes as a versioned [`WindowRules`] document,
+//! which the Settings app edits and stores at [`WINDOW_RULES_PATH`]:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "rules": [
+//!     {
+//!       "id": 1,
+//!       "name": "Terminals on desktop 2",
+//!       "match": { "app_id": "terminal" },
+//!       "actions": { "desktop": 1, "width": { "percent": 50 } }
+//!     }
+//!   ]
+//! }
+//! ```
+
+mod set;
+
+pub use set::WindowRules;
+
+use crate::window::{WindowConfig, WindowType};
+use serde::{Deserialize, Serialize};
+
+/// Unique rule identifier within a [`WindowRules`] list
+pub type RuleId = u32;
+
+/// Settings path where the rule document is stored
+pub const WINDOW_RULES_PATH: &str = "/system/settings/window_rules.json";
+
+/// Which windows a rule applies to
+///
+/// Every criterion that is set must hold. A matcher with no criteria
+/// matches every window.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleMatch {
+    /// Exact application ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    /// Case-insensitive substring of the window title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_contains: Option<String>,
+    /// Window type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_type: Option<WindowType>,
+}
+
+impl RuleMatch {
+    /// Check whether a window about to be created matches
+    pub fn matches(&self, config: &WindowConfig) -> bool {
+        if let Some(app_id) = &self.app_id {
+            if config.app_id != *app_id {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.title_contains {
+            if !config.title.to_lowercase().contains(&needle.to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(window_type) = self.window_type {
+            if config.window_type != window_type {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A window dimension, absolute or relative to the screen
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Length {
+    /// Canvas pixels
+    Px(f32),
+    /// Percentage of the screen dimension (0-100]
+    Percent(f32),
+}
+
+impl Length {
+    /// Resolve against a screen dimension
+    pub fn resolve(self, screen: f32) -> f32 {
+        match self {
+            Length::Px(px) => px,
+            Length::Percent(percent) => screen * percent / 100.0,
+        }
+    }
+
+    fn is_valid(self) -> bool {
+        match self {
+            Length::Px(px) => px.is_finite() && px > 0.0,
+            Length::Percent(percent) => percent.is_finite() && percent > 0.0 && percent <= 100.0,
+        }
+    }
+}
+
+/// Where a matching window is placed
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Placement {
+    /// Cascade from the last window, ignoring any requested position
+    Cascade,
+    /// Centered in the target desktop's view
+    Centered,
+    /// Fixed canvas position
+    At { x: f32, y: f32 },
+}
+
+/// What a rule does to matching windows
+///
+/// Unset actions leave the window as requested.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleActions {
+    /// Desktop index to open on (ignored if the desktop does not exist)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desktop: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<Length>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<Length>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<Placement>,
+    /// `false` locks the window at its initial size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resizable: Option<bool>,
+}
+
+impl RuleActions {
+    /// Overlay another rule's actions on top of these
+    pub fn merge(&mut self, other: &RuleActions) {
+        if other.desktop.is_some() {
+            self.desktop = other.desktop;
+        }
+        if other.width.is_some() {
+            self.width = other.width;
+        }
+        if other.height.is_some() {
+            self.height = other.height;
+        }
+        if other.placement.is_some() {
+            self.placement = other.placement;
+        }
+        if other.resizable.is_some() {
+            self.resizable = other.resizable;
+        }
+    }
+
+    /// Whether no action is set
+    pub fn is_empty(&self) -> bool {
+        *self == RuleActions::default()
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A single window rule
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowRule {
+    /// Assigned by [`WindowRules::add`]
+    #[serde(default)]
+    pub id: RuleId,
+    /// Label shown in Settings
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(rename = "match", default)]
+    pub matcher: RuleMatch,
+    #[serde(default)]
+    pub actions: RuleActions,
+}
+
+impl WindowRule {
+    /// Create an enabled rule
+    pub fn new(name: &str, matcher: RuleMatch, actions: RuleActions) -> Self {
+        Self {
+            id: 0,
+            name: name.to_string(),
+            enabled: true,
+            matcher,
+            actions,
+        }
+    }
+
+    /// Check the rule's values, returning why it is invalid
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let lengths = [self.actions.width, self.actions.height];
+        if lengths.iter().flatten().any(|l| !l.is_valid()) {
+            return Err("width and height must be positive, percentages at most 100");
+        }
+        if let Some(Placement::At { x, y }) = self.actions.placement {
+            if !x.is_finite() || !y.is_finite() {
+                return Err("placement coordinates must be finite");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(app_id: &str, title: &str) -> WindowConfig {
+        WindowConfig {
+            title: title.to_string(),
+            app_id: app_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_match_criteria_all_required() {
+        let matcher = RuleMatch {
+            app_id: Some("terminal".to_string()),
+            title_contains: Some("ssh".to_string()),
+            window_type: None,
+        };
+        assert!(matcher.matches(&config("terminal", "SSH session")));
+        assert!(!matcher.matches(&config("terminal", "Terminal")));
+        assert!(!matcher.matches(&config("browser", "ssh docs")));
+        assert!(RuleMatch::default().matches(&config("anything", "")));
+    }
+
+    #[test]
+    fn test_length_resolve() {
+        assert_eq!(Length::Px(640.0).resolve(1920.0), 640.0);
+        assert_eq!(Length::Percent(50.0).resolve(1920.0), 960.0);
+    }
+
+    #[test]
+    fn test_merge_later_wins() {
+        let mut actions = RuleActions {
+            desktop: Some(1),
+            width: Some(Length::Px(400.0)),
+            ..Default::default()
+        };
+        actions.merge(&RuleActions {
+            width: Some(Length::Percent(50.0)),
+            resizable: Some(false),
+            ..Default::default()
+        });
+        assert_eq!(actions.desktop, Some(1));
+        assert_eq!(actions.width, Some(Length::Percent(50.0)));
+        assert_eq!(actions.resizable, Some(false));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut rule = WindowRule::new("r", RuleMatch::default(), RuleActions::default());
+        assert!(rule.validate().is_ok());
+        rule.actions.width = Some(Length::Percent(150.0));
+        assert!(rule.validate().is_err());
+        rule.actions.width = Some(Length::Px(-1.0));
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_rule_json_shape() {
+        let json = r#"{
+            "name": "Settings centered",
+            "match": { "app_id": "settings" },
+            "actions": { "placement": { "kind": "centered" }, "resizable": false }
+        }"#;
+        let rule: WindowRule = serde_json::from_str(json).unwrap();
+        assert!(rule.enabled);
+        assert_eq!(rule.actions.placement, Some(Placement::Centered));
+        assert_eq!(rule.actions.resizable, Some(false));
+    }
+}
@@ -0,0 +1,288 @@
+//! Ordered, editable rule list
+
+use super::{RuleActions, RuleId, WindowRule};
+use crate::error::{DesktopError, DesktopResult};
+use crate::window::WindowConfig;
+use serde::{Deserialize, Serialize};
+
+/// The user's window rules, in evaluation order
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowRules {
+    /// Document version for migration support
+    pub version: u32,
+    rules: Vec<WindowRule>,
+}
+
+impl Default for WindowRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowRules {
+    /// Current document version
+    pub const CURRENT_VERSION: u32 = 1;
+
+    /// Create an empty rule list
+    pub fn new() -> Self {
+        Self {
+            version: Self::CURRENT_VERSION,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Check a deserialized rule document before using it
+    ///
+    /// Rejects documents from a newer version and documents containing
+    /// invalid rules; duplicate or missing IDs are renumbered.
+    pub fn validated(mut self) -> DesktopResult<Self> {
+        let rules = &mut self;
+        if rules.version > Self::CURRENT_VERSION {
+            return Err(DesktopError::PersistenceError(format!(
+                "window rules version {} is newer than supported version {}",
+                rules.version,
+                Self::CURRENT_VERSION
+            )));
+        }
+        for rule in &rules.rules {
+            rule.validate()
+                .map_err(|reason| DesktopError::InvalidOperation {
+                    op: "load_window_rules",
+                    reason,
+                })?;
+        }
+        rules.renumber_duplicates();
+        rules.version = Self::CURRENT_VERSION;
+        Ok(self)
+    }
+
+    /// Rules in evaluation order
+    pub fn rules(&self) -> &[WindowRule] {
+        &self.rules
+    }
+
+    /// Look up a rule by ID
+    pub fn get(&self, id: RuleId) -> Option<&WindowRule> {
+        self.rules.iter().find(|r| r.id == id)
+    }
+
+    /// Append a rule, assigning it a fresh ID
+    pub fn add(&mut self, mut rule: WindowRule) -> DesktopResult<RuleId> {
+        Self::check(&rule, "add_window_rule")?;
+        rule.id = self.next_id();
+        let id = rule.id;
+        self.rules.push(rule);
+        Ok(id)
+    }
+
+    /// Replace the rule with the same ID, keeping its position
+    pub fn update(&mut self, rule: WindowRule) -> DesktopResult<()> {
+        Self::check(&rule, "update_window_rule")?;
+        let index = self.index_of(rule.id)?;
+        self.rules[index] = rule;
+        Ok(())
+    }
+
+    /// Remove a rule
+    pub fn remove(&mut self, id: RuleId) -> DesktopResult<WindowRule> {
+        let index = self.index_of(id)?;
+        Ok(self.rules.remove(index))
+    }
+
+    /// Move a rule to a new position (clamped to the end of the list)
+    pub fn reorder(&mut self, id: RuleId, new_index: usize) -> DesktopResult<()> {
+        let index = self.index_of(id)?;
+        let rule = self.rules.remove(index);
+        let new_index = new_index.min(self.rules.len());
+        self.rules.insert(new_index, rule);
+        Ok(())
+    }
+
+    /// Enable or disable a rule
+    pub fn set_enabled(&mut self, id: RuleId, enabled: bool) -> DesktopResult<()> {
+        let index = self.index_of(id)?;
+        self.rules[index].enabled = enabled;
+        Ok(())
+    }
+
+    /// Combined actions of every enabled rule matching `config`
+    pub fn resolve(&self, config: &WindowConfig) -> RuleActions {
+        let mut actions = RuleActions::default();
+        for rule in self.rules.iter().filter(|r| r.enabled) {
+            if rule.matcher.matches(config) {
+                actions.merge(&rule.actions);
+            }
+        }
+        actions
+    }
+
+    fn check(rule: &WindowRule, op: &'static str) -> DesktopResult<()> {
+        rule.validate()
+            .map_err(|reason| DesktopError::InvalidOperation { op, reason })
+    }
+
+    fn index_of(&self, id: RuleId) -> DesktopResult<usize> {
+        self.rules
+            .iter()
+            .position(|r| r.id == id)
+            .ok_or(DesktopError::RuleNotFound(id))
+    }
+
+    fn next_id(&self) -> RuleId {
+        self.rules.iter().map(|r| r.id).max().unwrap_or(0) + 1
+    }
+
+    fn renumber_duplicates(&mut self) {
+        let mut seen = Vec::with_capacity(self.rules.len());
+        for i in 0..self.rules.len() {
+            let id = self.rules[i].id;
+            if id == 0 || seen.contains(&id) {
+                self.rules[i].id = self.next_id();
+            }
+            seen.push(self.rules[i].id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Length, RuleMatch};
+
+    fn rule_for(app_id: &str, actions: RuleActions) -> WindowRule {
+        WindowRule::new(
+            app_id,
+            RuleMatch {
+                app_id: Some(app_id.to_string()),
+                ..Default::default()
+            },
+            actions,
+        )
+    }
+
+    fn load(json: &str) -> DesktopResult<WindowRules> {
+        serde_json::from_str::<WindowRules>(json)
+            .unwrap()
+            .validated()
+    }
+
+    fn config(app_id: &str) -> WindowConfig {
+        WindowConfig {
+            app_id: app_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_add_assigns_ids() {
+        let mut rules = WindowRules::new();
+        let a = rules.add(rule_for("a", RuleActions::default())).unwrap();
+        let b = rules.add(rule_for("b", RuleActions::default())).unwrap();
+        assert_eq!((a, b), (1, 2));
+        rules.remove(a).unwrap();
+        assert_eq!(rules.add(rule_for("c", RuleActions::default())).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_missing_rule_errors() {
+        let mut rules = WindowRules::new();
+        assert_eq!(rules.remove(7), Err(DesktopError::RuleNotFound(7)));
+        assert!(rules.set_enabled(7, false).is_err());
+    }
+
+    #[test]
+    fn test_resolve_order_and_disabled() {
+        let mut rules = WindowRules::new();
+        let general = rules
+            .add(WindowRule::new(
+                "all",
+                RuleMatch::default(),
+                RuleActions {
+                    width: Some(Length::Px(500.0)),
+                    ..Default::default()
+                },
+            ))
+            .unwrap();
+        let specific = rules
+            .add(rule_for(
+                "terminal",
+                RuleActions {
+                    width: Some(Length::Percent(50.0)),
+                    desktop: Some(1),
+                    ..Default::default()
+                },
+            ))
+            .unwrap();
+
+        let actions = rules.resolve(&config("terminal"));
+        assert_eq!(actions.width, Some(Length::Percent(50.0)));
+        assert_eq!(actions.desktop, Some(1));
+        assert_eq!(
+            rules.resolve(&config("browser")).width,
+            Some(Length::Px(500.0))
+        );
+
+        // Putting the general rule last makes it win
+        rules.reorder(general, 5).unwrap();
+        assert_eq!(
+            rules.resolve(&config("terminal")).width,
+            Some(Length::Px(500.0))
+        );
+
+        rules.set_enabled(specific, false).unwrap();
+        assert_eq!(rules.resolve(&config("terminal")).desktop, None);
+    }
+
+    #[test]
+    fn test_invalid_rule_rejected() {
+        let mut rules = WindowRules::new();
+        let bad = rule_for(
+            "x",
+            RuleActions {
+                height: Some(Length::Percent(0.0)),
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            rules.add(bad),
+            Err(DesktopError::InvalidOperation { .. })
+        ));
+        assert!(rules.rules().is_empty());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut rules = WindowRules::new();
+        rules
+            .add(rule_for(
+                "settings",
+                RuleActions {
+                    resizable: Some(false),
+                    ..Default::default()
+                },
+            ))
+            .unwrap();
+        let json = serde_json::to_string(&rules).unwrap();
+        let restored: WindowRules = serde_json::from_str(&json).unwrap();
+        let restored = restored.validated().unwrap();
+        assert_eq!(restored, rules);
+    }
+
+    #[test]
+    fn test_validated_renumbers_and_checks_version() {
+        let json = r#"{"version": 1, "rules": [
+            {"id": 4, "name": "a"},
+            {"id": 4, "name": "b"},
+            {"name": "c"}
+        ]}"#;
+        let rules = load(json).unwrap();
+        let ids: Vec<RuleId> = rules.rules().iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![4, 5, 6]);
+
+        let newer = r#"{"version": 99, "rules": []}"#;
+        assert!(matches!(
+            load(newer),
+            Err(DesktopError::PersistenceError(_))
+        ));
+    }
+}
@@ -0,0 +1,173 @@
+//! Scripted automation of the desktop engine
+//!
+//! A script is a list of [`ScriptOp`]s - create a window, drag it, switch
+//! desktops, query state - applied in order to a [`DesktopEngine`]. Time is
+//! virtual: the [`ScriptClock`] only moves on `advance` and `settle`, so the
+//! same script always produces the same engine state. This makes the window
+//! manager testable end to end without a browser.
+//!
+//! Scripts are plain serde data, so the same script can be written in Rust
+//! (see [`ScriptRunner`]) or sent as JSON by an automation app:
+//!
+//! ```json
+//! [
+//!   {"op": "create_window", "title": "Notes", "app_id": "notes", "width": 640, "height": 480},
+//!   {"op": "switch_desktop", "index": 1},
+//!   {"op": "settle"},
+//!   {"op": "query"}
+//! ]
+//! ```
+//!
+//! Execution stops at the first failing step; the [`ScriptReport`] holds the
+//! outputs of every step that ran and the failure, if any.
+//!
+//! [`DesktopEngine`]: crate::DesktopEngine
+
+mod runner;
+mod state;
+
+pub use runner::{ScriptClock, ScriptRunner, FRAME_MS, SETTLE_LIMIT_MS};
+pub use state::{DesktopSnapshot, EngineState, WindowSnapshot};
+
+use crate::desktop::DesktopId;
+use crate::window::WindowId;
+use serde::{Deserialize, Serialize};
+
+/// A single automation step
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ScriptOp {
+    /// Create a window on the active desktop (cascaded if no position)
+    CreateWindow {
+        title: String,
+        app_id: String,
+        #[serde(default)]
+        x: Option<f32>,
+        #[serde(default)]
+        y: Option<f32>,
+        width: f32,
+        height: f32,
+    },
+    /// Launch an app with its default window configuration
+    LaunchApp { app_id: String },
+    /// Close a window
+    CloseWindow { window: WindowId },
+    /// Focus a window
+    FocusWindow { window: WindowId },
+    /// Move a window to a canvas position
+    MoveWindow { window: WindowId, x: f32, y: f32 },
+    /// Resize a window
+    ResizeWindow {
+        window: WindowId,
+        width: f32,
+        height: f32,
+    },
+    /// Minimize a window
+    MinimizeWindow { window: WindowId },
+    /// Maximize a window to the visible area
+    MaximizeWindow { window: WindowId },
+    /// Restore a minimized or maximized window
+    RestoreWindow { window: WindowId },
+    /// Create a desktop
+    CreateDesktop { name: String },
+    /// Switch to a desktop by index
+    SwitchDesktop { index: usize },
+    /// Zoom out to the void
+    EnterVoid,
+    /// Leave the void into a desktop by index
+    ExitVoid { index: usize },
+    /// Pan the viewport by a screen-space delta
+    Pan { dx: f32, dy: f32 },
+    /// Zoom around a screen-space anchor
+    Zoom { factor: f32, x: f32, y: f32 },
+    /// Pointer pressed at a screen position
+    PointerDown {
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        button: u8,
+        #[serde(default)]
+        ctrl: bool,
+        #[serde(default)]
+        shift: bool,
+    },
+    /// Pointer moved to a screen position
+    PointerMove { x: f32, y: f32 },
+    /// Pointer released
+    PointerUp,
+    /// Wheel scrolled at a screen position
+    Wheel {
+        dx: f32,
+        dy: f32,
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        ctrl: bool,
+    },
+    /// Start moving a window as if by its title bar
+    StartWindowDrag { window: WindowId, x: f32, y: f32 },
+    /// Start resizing a window from an edge or corner ("n", "se", ...)
+    StartWindowResize {
+        window: WindowId,
+        direction: String,
+        x: f32,
+        y: f32,
+    },
+    /// Start carrying a window in the void
+    StartWindowCarry { window: WindowId, x: f32, y: f32 },
+    /// Advance the virtual clock, ticking transitions frame by frame
+    Advance { ms: f64 },
+    /// Advance until no transition or animation is running
+    Settle,
+    /// Capture the engine state
+    Query,
+}
+
+/// What a successful step produced
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScriptOutput {
+    /// Step had no result
+    Done,
+    /// A window was created
+    Window { id: WindowId },
+    /// A desktop was created
+    Desktop { id: DesktopId },
+    /// Engine state from a `query` step
+    State(EngineState),
+}
+
+/// The step that stopped a script
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScriptFailure {
+    /// Index of the failing step
+    pub step: usize,
+    /// Why it failed
+    pub message: String,
+}
+
+/// Result of running a script
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScriptReport {
+    /// One output per step that succeeded
+    pub outputs: Vec<ScriptOutput>,
+    /// The failing step, if the script stopped early
+    pub failure: Option<ScriptFailure>,
+    /// Virtual clock when the script ended
+    pub now_ms: f64,
+}
+
+impl ScriptReport {
+    /// Whether every step succeeded
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// The last state captured by a `query` step
+    pub fn last_state(&self) -> Option<&EngineState> {
+        self.outputs.iter().rev().find_map(|output| match output {
+            ScriptOutput::State(state) => Some(state),
+            _ => None,
+        })
+    }
+}
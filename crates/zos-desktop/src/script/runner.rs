@@ -0,0 +1,284 @@
+//! Script execution against a desktop engine
+
+use super::{EngineState, ScriptFailure, ScriptOp, ScriptOutput, ScriptReport};
+use crate::engine::DesktopEngine;
+use crate::error::{DesktopError, DesktopResult};
+use crate::math::{Size, Vec2};
+use crate::window::{WindowConfig, WindowId};
+
+/// Length of one virtual frame when advancing time
+pub const FRAME_MS: f64 = 16.0;
+
+/// Longest a `settle` step waits before failing
+pub const SETTLE_LIMIT_MS: f64 = 10_000.0;
+
+/// Virtual clock for scripted runs
+///
+/// Only moves when a script advances it, so transitions progress exactly the
+/// same way on every run.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScriptClock {
+    now_ms: f64,
+}
+
+impl ScriptClock {
+    /// Create a clock starting at `now_ms`
+    pub fn starting_at(now_ms: f64) -> Self {
+        Self { now_ms }
+    }
+
+    /// Current virtual time
+    #[inline]
+    pub fn now_ms(&self) -> f64 {
+        self.now_ms
+    }
+}
+
+impl DesktopEngine {
+    /// Run a script, stopping at the first failing step
+    pub fn run_script(&mut self, clock: &mut ScriptClock, ops: &[ScriptOp]) -> ScriptReport {
+        let mut report = ScriptReport::default();
+        for (step, op) in ops.iter().enumerate() {
+            match self.apply_script_op(clock, op) {
+                Ok(output) => report.outputs.push(output),
+                Err(e) => {
+                    report.failure = Some(ScriptFailure {
+                        step,
+                        message: e.to_string(),
+                    });
+                    break;
+                }
+            }
+        }
+        report.now_ms = clock.now_ms;
+        report
+    }
+
+    /// Apply a single script step
+    pub fn apply_script_op(
+        &mut self,
+        clock: &mut ScriptClock,
+        op: &ScriptOp,
+    ) -> DesktopResult<ScriptOutput> {
+        let now = clock.now_ms;
+        match op {
+            ScriptOp::CreateWindow {
+                title,
+                app_id,
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let position = match (x, y) {
+                    (Some(x), Some(y)) => Some(Vec2::new(*x, *y)),
+                    _ => None,
+                };
+                let id = self.create_window(WindowConfig {
+                    title: title.clone(),
+                    position,
+                    size: Size::new(*width, *height),
+                    app_id: app_id.clone(),
+                    ..Default::default()
+                });
+                return Ok(ScriptOutput::Window { id });
+            }
+            ScriptOp::LaunchApp { app_id } => {
+                let id = self.launch_app(app_id);
+                return Ok(ScriptOutput::Window { id });
+            }
+            ScriptOp::CloseWindow { window } => {
+                self.script_window(*window)?;
+                self.close_window(*window);
+            }
+            ScriptOp::FocusWindow { window } => {
+                self.script_window(*window)?;
+                self.focus_window(*window);
+            }
+            ScriptOp::MoveWindow { window, x, y } => {
+                self.script_window(*window)?;
+                self.move_window(*window, *x, *y);
+            }
+            ScriptOp::ResizeWindow {
+                window,
+                width,
+                height,
+            } => {
+                self.script_window(*window)?;
+                self.resize_window(*window, *width, *height);
+            }
+            ScriptOp::MinimizeWindow { window } => {
+                self.script_window(*window)?;
+                self.minimize_window(*window);
+            }
+            ScriptOp::MaximizeWindow { window } => {
+                self.script_window(*window)?;
+                self.maximize_window(*window);
+            }
+            ScriptOp::RestoreWindow { window } => {
+                self.script_window(*window)?;
+                self.restore_window(*window);
+            }
+            ScriptOp::CreateDesktop { name } => {
+                let id = self.create_desktop(name);
+                return Ok(ScriptOutput::Desktop { id });
+            }
+            ScriptOp::SwitchDesktop { index } => {
+                self.script_desktop_index(*index)?;
+                self.switch_desktop(*index, now);
+            }
+            ScriptOp::EnterVoid => self.enter_void(now),
+            ScriptOp::ExitVoid { index } => {
+                self.script_desktop_index(*index)?;
+                self.exit_void(*index, now);
+            }
+            ScriptOp::Pan { dx, dy } => {
+                self.pan(*dx, *dy);
+                self.mark_activity(now);
+            }
+            ScriptOp::Zoom { factor, x, y } => {
+                self.zoom_at(*factor, *x, *y);
+                self.mark_activity(now);
+            }
+            ScriptOp::PointerDown {
+                x,
+                y,
+                button,
+                ctrl,
+                shift,
+            } => {
+                self.handle_pointer_down(*x, *y, *button, *ctrl, *shift);
+            }
+            ScriptOp::PointerMove { x, y } => {
+                self.handle_pointer_move(*x, *y);
+            }
+            ScriptOp::PointerUp => {
+                self.handle_pointer_up();
+            }
+            ScriptOp::Wheel { dx, dy, x, y, ctrl } => {
+                if self.handle_wheel(*dx, *dy, *x, *y, *ctrl).is_handled() {
+                    self.mark_activity(now);
+                }
+            }
+            ScriptOp::StartWindowDrag { window, x, y } => {
+                self.script_window(*window)?;
+                self.start_move_drag(*window, *x, *y);
+            }
+            ScriptOp::StartWindowResize {
+                window,
+                direction,
+                x,
+                y,
+            } => {
+                self.script_window(*window)?;
+                self.start_resize_drag(*window, direction, *x, *y);
+            }
+            ScriptOp::StartWindowCarry { window, x, y } => {
+                self.script_window(*window)?;
+                self.start_window_carry(*window, *x, *y);
+            }
+            ScriptOp::Advance { ms } => {
+                if !ms.is_finite() || *ms < 0.0 {
+                    return Err(DesktopError::InvalidOperation {
+                        op: "advance",
+                        reason: "duration must be a finite, non-negative number",
+                    });
+                }
+                self.advance_script_clock(clock, now + ms);
+            }
+            ScriptOp::Settle => {
+                let deadline = now + SETTLE_LIMIT_MS;
+                while self.is_animating(clock.now_ms) {
+                    if clock.now_ms >= deadline {
+                        return Err(DesktopError::InvalidOperation {
+                            op: "settle",
+                            reason: "animations still running after the settle limit",
+                        });
+                    }
+                    self.advance_script_clock(clock, clock.now_ms + FRAME_MS);
+                }
+            }
+            ScriptOp::Query => {
+                return Ok(ScriptOutput::State(EngineState::capture(self, now)));
+            }
+        }
+        Ok(ScriptOutput::Done)
+    }
+
+    /// Move the clock to `until`, ticking transitions once per frame
+    fn advance_script_clock(&mut self, clock: &mut ScriptClock, until: f64) {
+        while clock.now_ms < until {
+            clock.now_ms = (clock.now_ms + FRAME_MS).min(until);
+            self.tick_transition(clock.now_ms);
+        }
+    }
+
+    fn script_window(&self, id: WindowId) -> DesktopResult<()> {
+        match self.windows.get(id) {
+            Some(_) => Ok(()),
+            None => Err(DesktopError::WindowNotFound(id)),
+        }
+    }
+
+    fn script_desktop_index(&self, index: usize) -> DesktopResult<()> {
+        let count = self.desktops.count();
+        if index < count {
+            Ok(())
+        } else {
+            Err(DesktopError::DesktopIndexOutOfBounds { index, count })
+        }
+    }
+}
+
+/// An engine paired with its own virtual clock
+///
+/// Convenience wrapper for test runners:
+///
+/// ```rust
+/// use zos_desktop::script::{ScriptOp, ScriptRunner};
+///
+/// let mut runner = ScriptRunner::new(1920.0, 1080.0);
+/// let report = runner.run(&[ScriptOp::EnterVoid, ScriptOp::Settle, ScriptOp::Query]);
+/// assert_eq!(report.last_state().unwrap().view, "void");
+/// ```
+pub struct ScriptRunner {
+    engine: DesktopEngine,
+    clock: ScriptClock,
+}
+
+impl ScriptRunner {
+    /// Create an initialized engine with the clock at zero
+    pub fn new(width: f32, height: f32) -> Self {
+        let mut engine = DesktopEngine::new();
+        engine.init(width, height);
+        Self {
+            engine,
+            clock: ScriptClock::default(),
+        }
+    }
+
+    /// Run a script
+    pub fn run(&mut self, ops: &[ScriptOp]) -> ScriptReport {
+        self.engine.run_script(&mut self.clock, ops)
+    }
+
+    /// Apply a single step
+    pub fn step(&mut self, op: &ScriptOp) -> DesktopResult<ScriptOutput> {
+        self.engine.apply_script_op(&mut self.clock, op)
+    }
+
+    /// Capture the current engine state
+    pub fn state(&self) -> EngineState {
+        EngineState::capture(&self.engine, self.clock.now_ms)
+    }
+
+    /// The engine being driven
+    pub fn engine(&self) -> &DesktopEngine {
+        &self.engine
+    }
+
+    /// Current virtual time
+    pub fn now_ms(&self) -> f64 {
+        self.clock.now_ms
+    }
+}
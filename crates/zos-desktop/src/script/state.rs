@@ -0,0 +1,113 @@
+//! Engine state snapshots for script queries
+
+use crate::desktop::{DesktopId, ViewMode};
+use crate::engine::DesktopEngine;
+use crate::window::{WindowId, WindowState};
+use serde::{Deserialize, Serialize};
+
+/// A window as seen by a script
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowSnapshot {
+    pub id: WindowId,
+    pub title: String,
+    pub app_id: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub state: WindowState,
+    pub z_order: u32,
+    pub focused: bool,
+    /// Index of the desktop holding the window
+    pub desktop: Option<usize>,
+}
+
+/// A desktop as seen by a script
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DesktopSnapshot {
+    pub id: DesktopId,
+    pub name: String,
+    /// Windows on this desktop, in creation order
+    pub windows: Vec<WindowId>,
+}
+
+/// Observable engine state at a point in virtual time
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EngineState {
+    /// Virtual clock when the state was captured
+    pub now_ms: f64,
+    /// `"desktop"` or `"void"`
+    pub view: String,
+    /// Index of the active desktop
+    pub active_desktop: usize,
+    /// Whether a crossfade or camera animation is running
+    pub transitioning: bool,
+    /// Viewport center and zoom
+    pub camera_x: f32,
+    pub camera_y: f32,
+    pub zoom: f32,
+    pub focused_window: Option<WindowId>,
+    pub desktops: Vec<DesktopSnapshot>,
+    /// Windows ordered bottom to top
+    pub windows: Vec<WindowSnapshot>,
+}
+
+impl EngineState {
+    /// Capture the current state of an engine
+    pub fn capture(engine: &DesktopEngine, now_ms: f64) -> Self {
+        let desktops = engine.desktops();
+        let focused = engine.windows().focused();
+
+        let windows = engine
+            .windows()
+            .windows_by_z()
+            .into_iter()
+            .map(|w| WindowSnapshot {
+                id: w.id,
+                title: w.title.clone(),
+                app_id: w.app_id.clone(),
+                x: w.position.x,
+                y: w.position.y,
+                width: w.size.width,
+                height: w.size.height,
+                state: w.state,
+                z_order: w.z_order,
+                focused: focused == Some(w.id),
+                desktop: desktops
+                    .desktops()
+                    .iter()
+                    .position(|d| d.contains_window(w.id)),
+            })
+            .collect();
+
+        let viewport = engine.viewport();
+        Self {
+            now_ms,
+            view: match engine.get_view_mode() {
+                ViewMode::Desktop { .. } => "desktop".to_string(),
+                ViewMode::Void => "void".to_string(),
+            },
+            active_desktop: desktops.active_index(),
+            transitioning: engine.is_transitioning(),
+            camera_x: viewport.center.x,
+            camera_y: viewport.center.y,
+            zoom: viewport.zoom,
+            focused_window: focused,
+            desktops: desktops
+                .desktops()
+                .iter()
+                .map(|d| DesktopSnapshot {
+                    id: d.id,
+                    name: d.name.clone(),
+                    windows: d.windows.clone(),
+                })
+                .collect(),
+            windows,
+        }
+    }
+
+    /// Look up a window by ID
+    pub fn window(&self, id: WindowId) -> Option<&WindowSnapshot> {
+        self.windows.iter().find(|w| w.id == id)
+    }
+}
@@ -27,6 +27,11 @@ impl CameraAnimation {
         (elapsed / duration).clamp(0.0, 1.0)
     }
 
+    /// Jump to the end of the animation (reduced motion)
+    pub fn finish_now(&mut self, now_ms: f64) {
+        self.start_ms = now_ms - CAMERA_ANIMATION_DURATION_MS as f64;
+    }
+
     /// Check if animation is complete
     pub fn is_complete(&self, now_ms: f64) -> bool {
         self.progress(now_ms) >= 1.0
@@ -87,14 +87,23 @@ impl Crossfade {
         }
     }
 
+    /// Duration of this transition in milliseconds
+    pub fn duration_ms(&self) -> u32 {
+        match self.direction {
+            CrossfadeDirection::SwitchDesktop => DESKTOP_SWITCH_DURATION_MS,
+            _ => CROSSFADE_DURATION_MS,
+        }
+    }
+
     /// Get the progress (0.0 to 1.0)
     pub fn progress(&self, now_ms: f64) -> f32 {
         let elapsed = (now_ms - self.start_ms) as f32;
-        let duration = match self.direction {
-            CrossfadeDirection::SwitchDesktop => DESKTOP_SWITCH_DURATION_MS as f32,
-            _ => CROSSFADE_DURATION_MS as f32,
-        };
-        (elapsed / duration).clamp(0.0, 1.0)
+        (elapsed / self.duration_ms() as f32).clamp(0.0, 1.0)
+    }
+
+    /// Jump to the end of the transition (reduced motion)
+    pub fn finish_now(&mut self, now_ms: f64) {
+        self.start_ms = now_ms - self.duration_ms() as f64;
     }
 
     /// Check if transition is complete
@@ -206,4 +215,12 @@ mod tests {
         assert!(crossfade.progress(CROSSFADE_DURATION_MS as f64) >= 1.0);
         assert!(crossfade.is_complete(CROSSFADE_DURATION_MS as f64));
     }
+
+    #[test]
+    fn test_crossfade_finish_now() {
+        let mut crossfade = Crossfade::switch_desktop(0.0, 0, 1);
+        crossfade.finish_now(100.0);
+        assert!(crossfade.is_complete(100.0));
+        assert_eq!(crossfade.opacities(100.0), (1.0, 0.0));
+    }
 }
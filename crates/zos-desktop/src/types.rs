@@ -14,3 +14,9 @@ pub type WindowId = u64;
 /// Desktops are identified by a monotonically increasing 32-bit integer.
 /// Desktop IDs are globally unique within a `DesktopEngine` instance.
 pub type DesktopId = u32;
+
+/// Unique widget identifier
+///
+/// Widgets (sticky notes, clocks, ...) are numbered separately from
+/// windows. Widget IDs are unique across all desktops of a `DesktopEngine`.
+pub type WidgetId = u64;
@@ -6,8 +6,9 @@
 use wasm_bindgen::prelude::*;
 
 use crate::engine::DesktopEngine;
-use crate::math::{Size, Vec2};
-use crate::window::{WindowConfig, WindowState, WindowType};
+use crate::input::InputEvent;
+use crate::math::{Rect, Size, Vec2};
+use crate::window::{ResizeIncrement, WindowConfig, WindowState, WindowType};
 
 // Import js_sys::Date for timestamps
 #[wasm_bindgen]
@@ -103,6 +104,8 @@ impl DesktopController {
             size: Size::new(w, h),
             min_size: Some(Size::new(200.0, 150.0)),
             max_size: None,
+            aspect_ratio: None,
+            resize_increment: None,
             app_id: app_id.to_string(),
             process_id: None,
             content_interactive,
@@ -151,6 +154,30 @@ impl DesktopController {
         self.engine.resize_window(id, w, h);
     }
 
+    /// Update a window's resize constraints (from an app's
+    /// `MSG_APP_WINDOW_PROPERTIES` message)
+    ///
+    /// An `aspect_ratio` of 0 unlocks the ratio; a zero step disables
+    /// increments on that axis.
+    #[wasm_bindgen]
+    pub fn set_window_resize_constraints(
+        &mut self,
+        id: u64,
+        aspect_ratio: f32,
+        step_w: f32,
+        step_h: f32,
+        base_w: f32,
+        base_h: f32,
+    ) {
+        let aspect_ratio = (aspect_ratio > 0.0).then_some(aspect_ratio);
+        let increment = (step_w > 0.0 || step_h > 0.0).then(|| ResizeIncrement {
+            step: Size::new(step_w, step_h),
+            base: Size::new(base_w, base_h),
+        });
+        self.engine
+            .set_window_resize_constraints(id, aspect_ratio, increment);
+    }
+
     /// Minimize a window
     #[wasm_bindgen]
     pub fn minimize_window(&mut self, id: u64) {
@@ -169,6 +196,50 @@ impl DesktopController {
         self.engine.restore_window(id);
     }
 
+    /// Toggle fullscreen for a window (hides its frame and the taskbar)
+    #[wasm_bindgen]
+    pub fn toggle_fullscreen(&mut self, id: u64) {
+        self.engine.toggle_fullscreen(id);
+    }
+
+    /// Leave fullscreen for a window
+    #[wasm_bindgen]
+    pub fn exit_fullscreen(&mut self, id: u64) {
+        self.engine.exit_fullscreen(id);
+    }
+
+    /// Get the fullscreen window on the shown desktop, if any
+    #[wasm_bindgen]
+    pub fn get_fullscreen_window(&self) -> Option<u64> {
+        self.engine.fullscreen_window()
+    }
+
+    /// Bring a window in front of the user on behalf of an app (restores,
+    /// focuses and pans to it, following the focus policy if it's on another
+    /// desktop)
+    #[wasm_bindgen]
+    pub fn request_focus(&mut self, id: u64) {
+        self.engine.request_focus(id, date_now());
+    }
+
+    /// Set the focus policy ("switch_desktop" or "pull_window")
+    #[wasm_bindgen]
+    pub fn set_focus_policy(&mut self, policy: &str) -> bool {
+        match crate::engine::FocusPolicy::from_name(policy) {
+            Some(policy) => {
+                self.engine.set_focus_policy(policy);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the focus policy name
+    #[wasm_bindgen]
+    pub fn get_focus_policy(&self) -> String {
+        self.engine.focus_policy().name().to_string()
+    }
+
     /// Get the focused window ID
     #[wasm_bindgen]
     pub fn get_focused_window(&self) -> Option<u64> {
@@ -181,6 +252,25 @@ impl DesktopController {
         self.engine.pan_to_window(id, date_now());
     }
 
+    /// Animate the camera to fit a window on screen
+    #[wasm_bindgen]
+    pub fn zoom_to_window(&mut self, id: u64) {
+        self.engine.zoom_to_window(id, date_now());
+    }
+
+    /// Animate the camera to fit all windows on the active desktop
+    #[wasm_bindgen]
+    pub fn zoom_to_fit_all(&mut self) {
+        self.engine.zoom_to_fit_all(date_now());
+    }
+
+    /// Animate the camera to fit a canvas rectangle on screen
+    #[wasm_bindgen]
+    pub fn zoom_to_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.engine
+            .zoom_to_rect(Rect::new(x, y, width, height), date_now());
+    }
+
     /// Get all windows as JSON
     #[wasm_bindgen]
     pub fn get_windows_json(&self) -> String {
@@ -280,6 +370,124 @@ impl DesktopController {
             .set_desktop_background(desktop_index as usize, background);
     }
 
+    /// Move a window to another desktop
+    #[wasm_bindgen]
+    pub fn move_window_to_desktop(&mut self, window_id: u64, desktop_index: u32) -> bool {
+        self.engine
+            .move_window_to_desktop(window_id, desktop_index as usize)
+            .is_ok()
+    }
+
+    /// Rename a desktop
+    #[wasm_bindgen]
+    pub fn rename_desktop(&mut self, index: u32, name: &str) -> bool {
+        self.engine.rename_desktop(index as usize, name).is_ok()
+    }
+
+    /// Move a desktop to a new position in the void
+    #[wasm_bindgen]
+    pub fn move_desktop(&mut self, from: u32, to: u32) -> bool {
+        self.engine.move_desktop(from as usize, to as usize).is_ok()
+    }
+
+    /// Delete a desktop, moving its windows to the desktop at `rehome_to`
+    ///
+    /// Returns an empty string on success, otherwise the error message.
+    #[wasm_bindgen]
+    pub fn delete_desktop(&mut self, index: u32, rehome_to: u32) -> String {
+        match self
+            .engine
+            .delete_desktop(index as usize, rehome_to as usize)
+        {
+            Ok(()) => String::new(),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    /// Duplicate a desktop's layout, returning the copy's index (-1 on error)
+    #[wasm_bindgen]
+    pub fn duplicate_desktop(&mut self, index: u32) -> i32 {
+        self.engine
+            .duplicate_desktop(index as usize)
+            .map_or(-1, |i| i as i32)
+    }
+
+    /// Get a desktop's layout as a template JSON document
+    #[wasm_bindgen]
+    pub fn get_desktop_template_json(&self, index: u32) -> String {
+        self.engine
+            .desktop_template(index as usize)
+            .ok()
+            .and_then(|t| serde_json::to_string(&t).ok())
+            .unwrap_or_else(|| "null".to_string())
+    }
+
+    /// Create a desktop from a template JSON document
+    ///
+    /// Returns `{"desktop": index, "windows": [{"id", "appId"}]}` so the
+    /// caller can spawn and link app processes, or "null" if the template is
+    /// invalid.
+    #[wasm_bindgen]
+    pub fn create_desktop_from_template_json(&mut self, json: &str) -> String {
+        let Some(instance) = serde_json::from_str::<crate::desktop::DesktopTemplate>(json)
+            .ok()
+            .and_then(|t| self.engine.create_desktop_from_template(&t).ok())
+        else {
+            return "null".to_string();
+        };
+        let windows: Vec<_> = instance
+            .windows
+            .iter()
+            .map(|(id, app_id)| serde_json::json!({ "id": id, "appId": app_id }))
+            .collect();
+        serde_json::json!({ "desktop": instance.desktop, "windows": windows }).to_string()
+    }
+
+    /// Bind VFS folders (`[{"name", "path"}]`) to a desktop
+    ///
+    /// Returns an empty string on success, or the error message.
+    #[wasm_bindgen]
+    pub fn set_desktop_folders_json(&mut self, index: u32, json: &str) -> String {
+        let folders = match serde_json::from_str(json) {
+            Ok(folders) => folders,
+            Err(e) => return e.to_string(),
+        };
+        match self.engine.set_desktop_folders(index as usize, folders) {
+            Ok(()) => String::new(),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    /// Get the VFS folders bound to a desktop as JSON
+    #[wasm_bindgen]
+    pub fn get_desktop_folders_json(&self, index: u32) -> String {
+        self.engine
+            .desktop_folders(index as usize)
+            .ok()
+            .and_then(|f| serde_json::to_string(f).ok())
+            .unwrap_or_else(|| "[]".to_string())
+    }
+
+    /// Export the desktop list (order, names, cameras, backgrounds) as JSON
+    #[wasm_bindgen]
+    pub fn export_snapshot_json(&self) -> String {
+        serde_json::to_string(&self.engine.export_snapshot()).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Restore the desktop list from a snapshot JSON document
+    ///
+    /// Returns an empty string on success, otherwise the error message.
+    #[wasm_bindgen]
+    pub fn import_snapshot_json(&mut self, json: &str) -> String {
+        let result = serde_json::from_str::<crate::persistence::Snapshot>(json)
+            .map_err(|e| crate::DesktopError::SerializationError(e.to_string()))
+            .and_then(|snapshot| self.engine.import_snapshot(snapshot));
+        match result {
+            Ok(()) => String::new(),
+            Err(e) => e.to_string(),
+        }
+    }
+
     /// Get all desktops as JSON
     #[wasm_bindgen]
     pub fn get_desktops_json(&self) -> String {
@@ -384,41 +592,467 @@ impl DesktopController {
     /// Handle pointer down event
     #[wasm_bindgen]
     pub fn pointer_down(&mut self, x: f32, y: f32, button: u8, ctrl: bool, shift: bool) -> String {
-        let result = self.engine.handle_pointer_down(x, y, button, ctrl, shift);
+        let event = InputEvent::PointerDown {
+            x,
+            y,
+            button,
+            ctrl,
+            shift,
+        };
+        let result = self.engine.dispatch_input(&event, date_now());
         serde_json::to_string(&result).unwrap_or_else(|_| r#"{"type":"unhandled"}"#.to_string())
     }
 
     /// Handle pointer move event
     #[wasm_bindgen]
     pub fn pointer_move(&mut self, x: f32, y: f32) -> String {
-        let result = self.engine.handle_pointer_move(x, y);
+        let result = self
+            .engine
+            .dispatch_input(&InputEvent::PointerMove { x, y }, date_now());
         serde_json::to_string(&result).unwrap_or_else(|_| r#"{"type":"unhandled"}"#.to_string())
     }
 
     /// Handle pointer up event
     #[wasm_bindgen]
     pub fn pointer_up(&mut self) -> String {
-        let result = self.engine.handle_pointer_up();
+        let result = self
+            .engine
+            .dispatch_input(&InputEvent::PointerUp, date_now());
         serde_json::to_string(&result).unwrap_or_else(|_| r#"{"type":"unhandled"}"#.to_string())
     }
 
     /// Handle wheel event
     #[wasm_bindgen]
     pub fn wheel(&mut self, dx: f32, dy: f32, x: f32, y: f32, ctrl: bool) -> String {
-        let result = self.engine.handle_wheel(dx, dy, x, y, ctrl);
+        let event = InputEvent::Wheel { dx, dy, x, y, ctrl };
+        let result = self.engine.dispatch_input(&event, date_now());
         serde_json::to_string(&result).unwrap_or_else(|_| r#"{"type":"unhandled"}"#.to_string())
     }
 
     /// Start a window resize operation
     #[wasm_bindgen]
     pub fn start_window_resize(&mut self, window_id: u64, direction: &str, x: f32, y: f32) {
-        self.engine.start_resize_drag(window_id, direction, x, y);
+        let event = InputEvent::StartWindowResize {
+            window: window_id,
+            direction: direction.to_string(),
+            x,
+            y,
+        };
+        self.engine.dispatch_input(&event, date_now());
     }
 
     /// Start a window drag operation
     #[wasm_bindgen]
     pub fn start_window_drag(&mut self, window_id: u64, x: f32, y: f32) {
-        self.engine.start_move_drag(window_id, x, y);
+        let event = InputEvent::StartWindowDrag {
+            window: window_id,
+            x,
+            y,
+        };
+        self.engine.dispatch_input(&event, date_now());
+    }
+
+    /// Start carrying a window in the void; releasing over a desktop tile
+    /// moves the window there
+    #[wasm_bindgen]
+    pub fn start_window_carry(&mut self, window_id: u64, x: f32, y: f32) {
+        let event = InputEvent::StartWindowCarry {
+            window: window_id,
+            x,
+            y,
+        };
+        self.engine.dispatch_input(&event, date_now());
+    }
+
+    /// Get the desktop tile under a screen point in the void (-1 if none)
+    #[wasm_bindgen]
+    pub fn get_desktop_tile_at(&self, x: f32, y: f32) -> i32 {
+        self.engine.desktop_tile_at(x, y).map_or(-1, |i| i as i32)
+    }
+
+    // =========================================================================
+    // Minimap
+    // =========================================================================
+
+    /// Show or hide the minimap (its layout is included in `tick_frame` while shown)
+    #[wasm_bindgen]
+    pub fn set_minimap_visible(&mut self, visible: bool) {
+        self.engine.set_minimap_visible(visible);
+    }
+
+    /// Get the minimap layout as JSON ("null" while hidden)
+    #[wasm_bindgen]
+    pub fn get_minimap_json(&self) -> String {
+        serde_json::to_string(&self.build_minimap_json()).unwrap_or_else(|_| "null".to_string())
+    }
+
+    /// Center the camera on a normalized minimap point (click or drag)
+    #[wasm_bindgen]
+    pub fn minimap_navigate(&mut self, x: f32, y: f32) {
+        self.engine.minimap_navigate(x, y);
+    }
+
+    // =========================================================================
+    // Annotations
+    // =========================================================================
+
+    /// Select a tool from JSON (`{"type": "pen", "color", "width"}` or
+    /// `{"type": "eraser", "radius"}`), or stop annotating with "null"
+    #[wasm_bindgen]
+    pub fn set_annotation_tool_json(&mut self, json: &str) -> bool {
+        match serde_json::from_str(json) {
+            Ok(tool) => {
+                self.engine.set_annotation_tool(tool);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Start a stroke at a screen point; false if not annotating
+    #[wasm_bindgen]
+    pub fn begin_stroke(&mut self, x: f32, y: f32, pressure: f32) -> bool {
+        self.engine.begin_stroke(x, y, pressure)
+    }
+
+    /// Continue the stroke
+    #[wasm_bindgen]
+    pub fn extend_stroke(&mut self, x: f32, y: f32, pressure: f32) {
+        self.engine.extend_stroke(x, y, pressure);
+    }
+
+    /// Finish the stroke, returning its ID
+    #[wasm_bindgen]
+    pub fn end_stroke(&mut self) -> Option<u64> {
+        self.engine.end_stroke()
+    }
+
+    /// Remove the last stroke on the active desktop
+    #[wasm_bindgen]
+    pub fn undo_stroke(&mut self) -> bool {
+        self.engine.undo_stroke()
+    }
+
+    /// Remove all strokes from a desktop
+    #[wasm_bindgen]
+    pub fn clear_annotations(&mut self, desktop_index: u32) -> bool {
+        self.engine
+            .clear_annotations(desktop_index as usize)
+            .is_ok()
+    }
+
+    /// Get the active desktop's strokes as JSON (canvas coordinates)
+    ///
+    /// Refetch when `annotationRevision` in the frame data or the active
+    /// desktop changes.
+    #[wasm_bindgen]
+    pub fn get_annotations_json(&self) -> String {
+        serde_json::to_string(&self.engine.annotations()).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    // =========================================================================
+    // Widgets
+    // =========================================================================
+
+    /// Add a widget (kind as JSON, e.g. `{"type":"clock"}`) to a desktop
+    ///
+    /// Without a position the widget is centered in the desktop's view.
+    /// Returns the widget ID, or -1 on failure.
+    #[wasm_bindgen]
+    pub fn add_widget_json(
+        &mut self,
+        desktop_index: u32,
+        kind_json: &str,
+        x: Option<f32>,
+        y: Option<f32>,
+    ) -> f64 {
+        let position = x.zip(y).map(|(x, y)| Vec2::new(x, y));
+        serde_json::from_str::<crate::WidgetKind>(kind_json)
+            .ok()
+            .and_then(|kind| {
+                self.engine
+                    .add_widget(desktop_index as usize, kind, position)
+                    .ok()
+            })
+            .map(|id| id as f64)
+            .unwrap_or(-1.0)
+    }
+
+    /// Replace a widget's contents from kind JSON
+    #[wasm_bindgen]
+    pub fn update_widget_json(&mut self, id: u64, kind_json: &str) -> bool {
+        serde_json::from_str::<crate::WidgetKind>(kind_json)
+            .ok()
+            .is_some_and(|kind| self.engine.update_widget(id, kind).is_ok())
+    }
+
+    /// Move a widget to a canvas position
+    #[wasm_bindgen]
+    pub fn move_widget(&mut self, id: u64, x: f32, y: f32) -> bool {
+        self.engine.move_widget(id, Vec2::new(x, y)).is_ok()
+    }
+
+    /// Resize a widget
+    #[wasm_bindgen]
+    pub fn resize_widget(&mut self, id: u64, width: f32, height: f32) -> bool {
+        self.engine
+            .resize_widget(id, Size::new(width, height))
+            .is_ok()
+    }
+
+    /// Remove a widget
+    #[wasm_bindgen]
+    pub fn remove_widget(&mut self, id: u64) -> bool {
+        self.engine.remove_widget(id).is_ok()
+    }
+
+    /// Get a desktop's widgets as JSON (canvas coordinates)
+    #[wasm_bindgen]
+    pub fn get_widgets_json(&self, desktop_index: u32) -> String {
+        self.engine
+            .desktops
+            .desktops()
+            .get(desktop_index as usize)
+            .and_then(|d| serde_json::to_string(&d.widgets).ok())
+            .unwrap_or_else(|| "[]".to_string())
+    }
+
+    /// Get the topmost uncovered widget at a screen point
+    #[wasm_bindgen]
+    pub fn widget_at(&self, x: f32, y: f32) -> Option<u64> {
+        self.engine.widget_at(x, y)
+    }
+
+    /// Start dragging a widget from its DOM element
+    #[wasm_bindgen]
+    pub fn start_widget_drag(&mut self, id: u64, x: f32, y: f32) {
+        self.engine.start_widget_drag(id, x, y);
+    }
+
+    // =========================================================================
+    // Window Rules
+    // =========================================================================
+
+    /// Get the window rules document as JSON
+    #[wasm_bindgen]
+    pub fn get_window_rules_json(&self) -> String {
+        serde_json::to_string(self.engine.window_rules()).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Replace all window rules from a JSON document (as stored in settings)
+    ///
+    /// Returns an empty string on success, otherwise the error message.
+    #[wasm_bindgen]
+    pub fn set_window_rules_json(&mut self, json: &str) -> String {
+        let parsed = serde_json::from_str::<crate::rules::WindowRules>(json)
+            .map_err(|e| crate::DesktopError::SerializationError(e.to_string()))
+            .and_then(|rules| rules.validated());
+        match parsed {
+            Ok(rules) => {
+                self.engine.set_window_rules(rules);
+                String::new()
+            }
+            Err(e) => e.to_string(),
+        }
+    }
+
+    /// Add a window rule from JSON, returning its ID (0 if invalid)
+    #[wasm_bindgen]
+    pub fn add_window_rule(&mut self, rule_json: &str) -> u32 {
+        serde_json::from_str::<crate::rules::WindowRule>(rule_json)
+            .ok()
+            .and_then(|rule| self.engine.add_window_rule(rule).ok())
+            .unwrap_or(0)
+    }
+
+    /// Replace a window rule from JSON (matched by its `id`)
+    #[wasm_bindgen]
+    pub fn update_window_rule(&mut self, rule_json: &str) -> bool {
+        serde_json::from_str::<crate::rules::WindowRule>(rule_json)
+            .ok()
+            .is_some_and(|rule| self.engine.update_window_rule(rule).is_ok())
+    }
+
+    /// Remove a window rule
+    #[wasm_bindgen]
+    pub fn remove_window_rule(&mut self, id: u32) -> bool {
+        self.engine.remove_window_rule(id).is_ok()
+    }
+
+    /// Move a window rule to a new evaluation position
+    #[wasm_bindgen]
+    pub fn reorder_window_rule(&mut self, id: u32, index: u32) -> bool {
+        self.engine.reorder_window_rule(id, index as usize).is_ok()
+    }
+
+    // =========================================================================
+    // Accessibility
+    // =========================================================================
+
+    /// Get the display settings as JSON, with the color filter's matrix
+    ///
+    /// `color_matrix` is row-major, for the frontend's own color filter.
+    #[wasm_bindgen]
+    pub fn get_display_settings_json(&self) -> String {
+        let settings = self.engine.display_settings();
+        serde_json::json!({
+            "high_contrast": settings.high_contrast,
+            "color_filter": settings.color_filter,
+            "reduced_motion": settings.reduced_motion,
+            "color_matrix": settings.color_filter.matrix(),
+        })
+        .to_string()
+    }
+
+    /// Apply display settings from JSON (missing fields take their defaults)
+    #[wasm_bindgen]
+    pub fn set_display_settings_json(&mut self, json: &str) -> bool {
+        match serde_json::from_str::<crate::accessibility::DisplaySettings>(json) {
+            Ok(settings) => {
+                self.engine.set_display_settings(settings);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    // =========================================================================
+    // Keyboard
+    // =========================================================================
+
+    /// Handle a key press (`key` is `KeyboardEvent.key`)
+    ///
+    /// Returns `{"type":"handled"}`, `{"type":"unhandled"}` or
+    /// `{"type":"frontend","command":...}` for commands the frontend runs.
+    #[wasm_bindgen]
+    pub fn handle_key(
+        &mut self,
+        key: &str,
+        ctrl: bool,
+        shift: bool,
+        alt: bool,
+        meta: bool,
+    ) -> String {
+        let keys = crate::keyboard::KeyChord::new(key, ctrl, shift, alt, meta);
+        let result = self.engine.handle_key(&keys, date_now());
+        serde_json::to_string(&result).unwrap_or_else(|_| r#"{"type":"unhandled"}"#.to_string())
+    }
+
+    /// Run a keyboard command from JSON (e.g. `{"command":"next_desktop"}`)
+    #[wasm_bindgen]
+    pub fn run_key_command_json(&mut self, json: &str) -> String {
+        let result = match serde_json::from_str::<crate::keyboard::KeyCommand>(json) {
+            Ok(command) => self.engine.run_key_command(command, date_now()),
+            Err(_) => crate::keyboard::KeyResult::Unhandled,
+        };
+        serde_json::to_string(&result).unwrap_or_else(|_| r#"{"type":"unhandled"}"#.to_string())
+    }
+
+    /// Get the hotkey cheat sheet as JSON
+    ///
+    /// One entry per command: `category`, `description`, the command's own
+    /// fields and `keys` (every chord bound to it, e.g. `"Ctrl+ArrowLeft"`).
+    #[wasm_bindgen]
+    pub fn get_hotkeys_json(&self) -> String {
+        serde_json::to_string(&self.engine.cheat_sheet()).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Bind a chord such as `"Alt+J"` to a command given as JSON
+    ///
+    /// Returns an empty string on success, otherwise the error message.
+    #[wasm_bindgen]
+    pub fn bind_hotkey(&mut self, chord: &str, command_json: &str) -> String {
+        let parsed = crate::keyboard::KeyChord::parse(chord).and_then(|keys| {
+            serde_json::from_str::<crate::keyboard::KeyCommand>(command_json)
+                .map(|command| (keys, command))
+                .map_err(|e| crate::DesktopError::SerializationError(e.to_string()))
+        });
+        match parsed {
+            Ok((keys, command)) => {
+                self.engine.hotkeys_mut().bind(keys, command);
+                String::new()
+            }
+            Err(e) => e.to_string(),
+        }
+    }
+
+    /// Remove a chord's binding
+    #[wasm_bindgen]
+    pub fn unbind_hotkey(&mut self, chord: &str) -> bool {
+        crate::keyboard::KeyChord::parse(chord)
+            .is_ok_and(|keys| self.engine.hotkeys_mut().unbind(&keys))
+    }
+
+    // =========================================================================
+    // Automation
+    // =========================================================================
+
+    /// Run a JSON automation script (an array of `ScriptOp`) against the
+    /// live engine and return the `ScriptReport` as JSON.
+    ///
+    /// The script's virtual clock starts at the current time; transitions it
+    /// leaves running finish once wall-clock time catches up.
+    #[wasm_bindgen]
+    pub fn run_script(&mut self, script_json: &str) -> String {
+        let mut clock = crate::script::ScriptClock::starting_at(date_now());
+        let report = match serde_json::from_str::<Vec<crate::script::ScriptOp>>(script_json) {
+            Ok(ops) => self.engine.run_script(&mut clock, &ops),
+            Err(e) => crate::script::ScriptReport {
+                failure: Some(crate::script::ScriptFailure {
+                    step: 0,
+                    message: format!("invalid script: {}", e),
+                }),
+                now_ms: clock.now_ms(),
+                ..Default::default()
+            },
+        };
+        serde_json::to_string(&report).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    // =========================================================================
+    // Input Recording
+    // =========================================================================
+
+    /// Start recording pointer input (restarts a recording already running)
+    #[wasm_bindgen]
+    pub fn start_input_recording(&mut self) {
+        self.engine.start_input_recording(date_now());
+    }
+
+    /// Stop recording and return the `InputRecording` as JSON ("null" if
+    /// nothing was being recorded)
+    #[wasm_bindgen]
+    pub fn stop_input_recording(&mut self) -> String {
+        serde_json::to_string(&self.engine.stop_input_recording())
+            .unwrap_or_else(|_| "null".to_string())
+    }
+
+    /// Check if input is being recorded
+    #[wasm_bindgen]
+    pub fn is_recording_input(&self) -> bool {
+        self.engine.is_recording_input()
+    }
+
+    /// Replay an `InputRecording` (JSON) on a virtual clock and return the
+    /// `ScriptReport` as JSON, like `run_script`
+    #[wasm_bindgen]
+    pub fn replay_input_recording(&mut self, json: &str) -> String {
+        let mut clock = crate::script::ScriptClock::starting_at(date_now());
+        let report = match serde_json::from_str::<crate::input::InputRecording>(json)
+            .map_err(|e| crate::DesktopError::SerializationError(e.to_string()))
+            .and_then(|recording| recording.validated())
+        {
+            Ok(recording) => self.engine.replay_input(&mut clock, &recording),
+            Err(e) => crate::script::ScriptReport {
+                failure: Some(crate::script::ScriptFailure {
+                    step: 0,
+                    message: format!("invalid recording: {}", e),
+                }),
+                now_ms: clock.now_ms(),
+                ..Default::default()
+            },
+        };
+        serde_json::to_string(&report).unwrap_or_else(|_| "{}".to_string())
     }
 
     // =========================================================================
@@ -432,6 +1066,7 @@ impl DesktopController {
         self.engine.tick_transition(now);
 
         let windows = self.build_windows_json(now);
+        let widgets = self.build_widgets_json(now);
         let view_mode = self.get_view_mode_str();
         let workspace_info = self.build_workspace_info_json(now);
         let workspace_dims = self.build_workspace_dimensions_json();
@@ -442,10 +1077,15 @@ impl DesktopController {
                 "zoom": self.engine.viewport.zoom
             },
             "windows": windows,
+            "widgets": widgets,
             "animating": self.engine.is_animating(now),
             "transitioning": self.engine.is_animating_viewport(),
             "showVoid": self.engine.should_show_void(),
             "viewMode": view_mode,
+            "fullscreenWindow": self.engine.fullscreen_window(),
+            "minimap": self.build_minimap_json(),
+            "carryTarget": self.engine.carry_drop_target(),
+            "annotationRevision": self.engine.annotation_revision(),
             "workspaceInfo": workspace_info,
             "workspaceDimensions": workspace_dims
         }))
@@ -462,6 +1102,47 @@ impl DesktopController {
             .collect()
     }
 
+    /// Build JSON array of widget screen rects
+    fn build_widgets_json(&self, now: f64) -> Vec<serde_json::Value> {
+        self.engine
+            .get_widget_screen_rects(now)
+            .into_iter()
+            .map(|r| {
+                serde_json::json!({
+                    "id": r.id,
+                    "kind": r.kind,
+                    "opacity": r.opacity,
+                    "screenRect": rect_to_json(&r.screen_rect)
+                })
+            })
+            .collect()
+    }
+
+    /// Build minimap JSON (null while the minimap is hidden)
+    fn build_minimap_json(&self) -> serde_json::Value {
+        let layout = match self.engine.minimap_layout() {
+            Some(layout) => layout,
+            None => return serde_json::Value::Null,
+        };
+        let windows: Vec<serde_json::Value> = layout
+            .windows
+            .iter()
+            .map(|w| {
+                serde_json::json!({
+                    "id": w.id,
+                    "focused": w.focused,
+                    "rect": rect_to_json(&w.rect)
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "aspectRatio": layout.aspect_ratio(),
+            "windows": windows,
+            "viewport": rect_to_json(&layout.viewport)
+        })
+    }
+
     /// Get the view mode as a string
     fn get_view_mode_str(&self) -> &'static str {
         if self.engine.is_transitioning() {
@@ -516,6 +1197,7 @@ fn build_window_rect_json(
         "zOrder": z_order,
         "opacity": r.opacity,
         "contentInteractive": r.content_interactive,
+        "resizable": r.resizable,
         "screenRect": {
             "x": r.screen_rect.x,
             "y": r.screen_rect.y,
@@ -525,6 +1207,15 @@ fn build_window_rect_json(
     })
 }
 
+fn rect_to_json(rect: &Rect) -> serde_json::Value {
+    serde_json::json!({
+        "x": rect.x,
+        "y": rect.y,
+        "width": rect.width,
+        "height": rect.height
+    })
+}
+
 fn window_to_json(window: &crate::window::Window, focused_id: Option<u64>) -> serde_json::Value {
     serde_json::json!({
         "id": window.id,
@@ -536,6 +1227,7 @@ fn window_to_json(window: &crate::window::Window, focused_id: Option<u64>) -> se
         "state": window_state_to_str(window.state),
         "windowType": window_type_to_str(window.window_type),
         "zOrder": window.z_order,
+        "resizable": window.is_resizable(),
         "focused": focused_id == Some(window.id)
     })
 }
@@ -0,0 +1,121 @@
+//! Per-desktop widget layer
+
+use super::{Widget, WidgetId};
+use crate::math::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Widgets on one desktop, back to front
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WidgetLayer {
+    widgets: Vec<Widget>,
+}
+
+impl WidgetLayer {
+    /// Create an empty layer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a widget on top
+    pub fn insert(&mut self, widget: Widget) {
+        self.widgets.push(widget);
+    }
+
+    /// Remove a widget by ID
+    pub fn remove(&mut self, id: WidgetId) -> Option<Widget> {
+        let index = self.widgets.iter().position(|w| w.id == id)?;
+        Some(self.widgets.remove(index))
+    }
+
+    /// Get a widget by ID
+    pub fn get(&self, id: WidgetId) -> Option<&Widget> {
+        self.widgets.iter().find(|w| w.id == id)
+    }
+
+    /// Get a widget by ID mutably
+    pub fn get_mut(&mut self, id: WidgetId) -> Option<&mut Widget> {
+        self.widgets.iter_mut().find(|w| w.id == id)
+    }
+
+    /// Bring a widget to the top of the layer
+    pub fn raise(&mut self, id: WidgetId) {
+        if let Some(widget) = self.remove(id) {
+            self.widgets.push(widget);
+        }
+    }
+
+    /// Topmost widget containing a canvas point
+    pub fn widget_at(&self, point: Vec2) -> Option<WidgetId> {
+        self.widgets
+            .iter()
+            .rev()
+            .find(|w| w.rect().contains(point))
+            .map(|w| w.id)
+    }
+
+    /// Move all widgets of another layer on top of this one
+    pub fn append(&mut self, other: &mut WidgetLayer) {
+        self.widgets.append(&mut other.widgets);
+    }
+
+    /// All widgets, back to front
+    #[inline]
+    pub fn widgets(&self) -> &[Widget] {
+        &self.widgets
+    }
+
+    /// Highest widget ID in the layer
+    pub fn max_id(&self) -> Option<WidgetId> {
+        self.widgets.iter().map(|w| w.id).max()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Size;
+    use crate::widget::WidgetKind;
+
+    fn widget(id: WidgetId, x: f32) -> Widget {
+        Widget::new(
+            id,
+            WidgetKind::Clock {
+                show_seconds: false,
+            },
+            Vec2::new(x, 0.0),
+            Size::new(200.0, 100.0),
+        )
+    }
+
+    #[test]
+    fn test_widget_at_prefers_top() {
+        let mut layer = WidgetLayer::new();
+        layer.insert(widget(1, 0.0));
+        layer.insert(widget(2, 100.0));
+
+        assert_eq!(layer.widget_at(Vec2::new(150.0, 50.0)), Some(2));
+        layer.raise(1);
+        assert_eq!(layer.widget_at(Vec2::new(150.0, 50.0)), Some(1));
+        assert_eq!(layer.widget_at(Vec2::new(150.0, 500.0)), None);
+    }
+
+    #[test]
+    fn test_remove_and_append() {
+        let mut a = WidgetLayer::new();
+        let mut b = WidgetLayer::new();
+        a.insert(widget(1, 0.0));
+        b.insert(widget(2, 0.0));
+
+        a.append(&mut b);
+        assert!(b.is_empty());
+        assert_eq!(a.max_id(), Some(2));
+        assert_eq!(a.remove(1).map(|w| w.id), Some(1));
+        assert!(a.remove(1).is_none());
+    }
+}
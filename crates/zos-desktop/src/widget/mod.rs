@@ -0,0 +1,16 @@
+//! Canvas widgets
+//!
+//! Widgets are small items that live on a desktop's canvas below the
+//! windows: sticky notes, clocks, resource monitors. Unlike windows they
+//! have no backing process, no title bar and no focus; the engine only
+//! tracks their kind, position and size, hit-tests them, and persists them
+//! with the desktop. The frontend renders their contents.
+
+mod layer;
+mod types;
+
+pub use layer::WidgetLayer;
+pub use types::{Widget, WidgetKind, MIN_WIDGET_SIZE};
+
+// Re-export WidgetId from crate types module, like WindowId
+pub use crate::types::WidgetId;
@@ -0,0 +1,125 @@
+//! Widget types
+
+use super::WidgetId;
+use crate::math::{Rect, Size, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// Smallest size a widget can be resized to
+pub const MIN_WIDGET_SIZE: Size = Size::new(80.0, 60.0);
+
+/// What a widget shows
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WidgetKind {
+    /// Editable note
+    StickyNote {
+        #[serde(default)]
+        text: String,
+        /// CSS color of the note
+        #[serde(default = "default_note_color")]
+        color: String,
+    },
+    /// Wall clock in the system time zone
+    Clock {
+        #[serde(default)]
+        show_seconds: bool,
+    },
+    /// CPU and memory usage
+    ResourceMonitor,
+}
+
+fn default_note_color() -> String {
+    "#f7e37a".to_string()
+}
+
+impl WidgetKind {
+    /// Size a new widget of this kind opens with
+    pub fn default_size(&self) -> Size {
+        match self {
+            WidgetKind::StickyNote { .. } => Size::new(220.0, 200.0),
+            WidgetKind::Clock { .. } => Size::new(180.0, 80.0),
+            WidgetKind::ResourceMonitor => Size::new(260.0, 140.0),
+        }
+    }
+
+    /// Kind name as used by the frontend
+    pub fn name(&self) -> &'static str {
+        match self {
+            WidgetKind::StickyNote { .. } => "sticky_note",
+            WidgetKind::Clock { .. } => "clock",
+            WidgetKind::ResourceMonitor => "resource_monitor",
+        }
+    }
+}
+
+/// A widget on a desktop's canvas
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Widget {
+    pub id: WidgetId,
+    pub kind: WidgetKind,
+    /// Position in desktop-local canvas coordinates
+    pub position: Vec2,
+    pub size: Size,
+}
+
+impl Widget {
+    /// Create a widget, clamping its size to [`MIN_WIDGET_SIZE`]
+    pub fn new(id: WidgetId, kind: WidgetKind, position: Vec2, size: Size) -> Self {
+        Self {
+            id,
+            kind,
+            position,
+            size: clamp_size(size),
+        }
+    }
+
+    /// Bounding rect in canvas coordinates
+    #[inline]
+    pub fn rect(&self) -> Rect {
+        Rect::from_pos_size(self.position, self.size)
+    }
+
+    /// Resize, clamping to [`MIN_WIDGET_SIZE`]
+    pub fn set_size(&mut self, size: Size) {
+        self.size = clamp_size(size);
+    }
+}
+
+fn clamp_size(size: Size) -> Size {
+    Size::new(
+        size.width.max(MIN_WIDGET_SIZE.width),
+        size.height.max(MIN_WIDGET_SIZE.height),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_clamped() {
+        let mut widget = Widget::new(
+            1,
+            WidgetKind::ResourceMonitor,
+            Vec2::ZERO,
+            Size::new(10.0, 500.0),
+        );
+        assert_eq!(widget.size, Size::new(80.0, 500.0));
+        widget.set_size(Size::new(300.0, 1.0));
+        assert_eq!(widget.size, Size::new(300.0, 60.0));
+    }
+
+    #[test]
+    fn test_kind_json() {
+        let kind: WidgetKind =
+            serde_json::from_str(r#"{"type":"sticky_note","text":"hi"}"#).unwrap();
+        assert_eq!(
+            kind,
+            WidgetKind::StickyNote {
+                text: "hi".to_string(),
+                color: "#f7e37a".to_string(),
+            }
+        );
+        assert_eq!(kind.name(), "sticky_note");
+    }
+}
@@ -1,6 +1,6 @@
 //! Window configuration for creation
 
-use super::WindowType;
+use super::{ResizeIncrement, WindowType};
 use crate::math::{Size, Vec2};
 
 /// Configuration for creating a window
@@ -16,6 +16,10 @@ pub struct WindowConfig {
     pub min_size: Option<Size>,
     /// Maximum size constraint
     pub max_size: Option<Size>,
+    /// Locked width / height ratio
+    pub aspect_ratio: Option<f32>,
+    /// Size steps (e.g. terminal cells)
+    pub resize_increment: Option<ResizeIncrement>,
     /// Application identifier for routing
     pub app_id: String,
     /// Associated process ID
@@ -0,0 +1,234 @@
+//! Resize constraints
+//!
+//! A window's size is limited by its min/max size, and optionally by an
+//! aspect-ratio lock (video, presentations) and resize increments (terminal
+//! cells). Constraints are applied after every resize, so a drag and a
+//! programmatic resize end up at the same size.
+
+use crate::math::Size;
+use serde::{Deserialize, Serialize};
+
+/// Axis the user is dragging; the other axis follows an aspect-ratio lock
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeAxis {
+    Width,
+    Height,
+}
+
+impl ResizeAxis {
+    /// Axis with the larger relative change from `from` to `to`
+    pub fn dominant(from: Size, to: Size) -> Self {
+        let dw = ((to.width - from.width) / from.width.max(1.0)).abs();
+        let dh = ((to.height - from.height) / from.height.max(1.0)).abs();
+        if dh > dw {
+            ResizeAxis::Height
+        } else {
+            ResizeAxis::Width
+        }
+    }
+}
+
+/// Size steps: valid sizes are `base + n * step` on each axis
+///
+/// For a terminal, `step` is the cell size and `base` the frame and padding
+/// around the grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResizeIncrement {
+    pub step: Size,
+    pub base: Size,
+}
+
+/// Everything limiting a window's size
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ResizeConstraints {
+    pub min_size: Size,
+    pub max_size: Option<Size>,
+    /// Locked width / height
+    pub aspect_ratio: Option<f32>,
+    pub increment: Option<ResizeIncrement>,
+}
+
+impl ResizeConstraints {
+    /// Constrain a requested size
+    ///
+    /// `axis` is the one the user controls: it is clamped and snapped first,
+    /// then the other axis is derived from the aspect ratio (if locked).
+    pub fn apply(&self, size: Size, axis: ResizeAxis) -> Size {
+        let mut width = self.clamp_width(size.width);
+        let mut height = self.clamp_height(size.height);
+
+        match self.aspect_ratio {
+            Some(ratio) => match axis {
+                ResizeAxis::Width => {
+                    width = self.snap_width(width);
+                    height = self.clamp_height(width / ratio);
+                    width = self.clamp_width(height * ratio);
+                }
+                ResizeAxis::Height => {
+                    height = self.snap_height(height);
+                    width = self.clamp_width(height * ratio);
+                    height = self.clamp_height(width / ratio);
+                }
+            },
+            None => {
+                width = self.snap_width(width);
+                height = self.snap_height(height);
+            }
+        }
+
+        Size::new(width, height)
+    }
+
+    fn clamp_width(&self, width: f32) -> f32 {
+        let width = width.max(self.min_size.width);
+        match self.max_size {
+            Some(max) => width.min(max.width),
+            None => width,
+        }
+    }
+
+    fn clamp_height(&self, height: f32) -> f32 {
+        let height = height.max(self.min_size.height);
+        match self.max_size {
+            Some(max) => height.min(max.height),
+            None => height,
+        }
+    }
+
+    fn snap_width(&self, width: f32) -> f32 {
+        match self.increment {
+            Some(inc) => snap(
+                width,
+                inc.base.width,
+                inc.step.width,
+                self.min_size.width,
+                self.max_size.map(|m| m.width),
+            ),
+            None => width,
+        }
+    }
+
+    fn snap_height(&self, height: f32) -> f32 {
+        match self.increment {
+            Some(inc) => snap(
+                height,
+                inc.base.height,
+                inc.step.height,
+                self.min_size.height,
+                self.max_size.map(|m| m.height),
+            ),
+            None => height,
+        }
+    }
+}
+
+/// Round `value` down to `base + n * step`, staying within min/max when a
+/// step fits between them
+fn snap(value: f32, base: f32, step: f32, min: f32, max: Option<f32>) -> f32 {
+    if step <= 0.0 || value <= base {
+        return value;
+    }
+    // Small epsilon keeps already-snapped values stable under float error
+    let steps = ((value - base) / step + 1e-3).floor();
+    let mut snapped = base + steps * step;
+    if snapped < min {
+        let up = base + ((min - base) / step - 1e-3).ceil() * step;
+        if max.is_none_or(|max| up <= max) {
+            snapped = up;
+        } else {
+            snapped = min;
+        }
+    }
+    snapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constraints() -> ResizeConstraints {
+        ResizeConstraints {
+            min_size: Size::new(200.0, 150.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_min_max_clamp() {
+        let c = ResizeConstraints {
+            max_size: Some(Size::new(800.0, 600.0)),
+            ..constraints()
+        };
+        assert_eq!(
+            c.apply(Size::new(50.0, 1000.0), ResizeAxis::Width),
+            Size::new(200.0, 600.0)
+        );
+    }
+
+    #[test]
+    fn test_aspect_ratio_follows_axis() {
+        let c = ResizeConstraints {
+            aspect_ratio: Some(16.0 / 9.0),
+            ..constraints()
+        };
+        let by_width = c.apply(Size::new(1600.0, 100.0), ResizeAxis::Width);
+        assert!((by_width.height - 900.0).abs() < 0.01);
+
+        let by_height = c.apply(Size::new(100.0, 450.0), ResizeAxis::Height);
+        assert!((by_height.width - 800.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_aspect_ratio_respects_max() {
+        let c = ResizeConstraints {
+            max_size: Some(Size::new(2000.0, 900.0)),
+            aspect_ratio: Some(2.0),
+            ..constraints()
+        };
+        let size = c.apply(Size::new(1900.0, 100.0), ResizeAxis::Width);
+        assert_eq!(size, Size::new(1800.0, 900.0));
+    }
+
+    #[test]
+    fn test_increments_snap_down() {
+        let c = ResizeConstraints {
+            increment: Some(ResizeIncrement {
+                step: Size::new(8.0, 16.0),
+                base: Size::new(20.0, 40.0),
+            }),
+            ..constraints()
+        };
+        // 20 + 8 * 72 = 596, 40 + 16 * 21 = 376
+        let size = c.apply(Size::new(603.0, 391.0), ResizeAxis::Width);
+        assert_eq!(size, Size::new(596.0, 376.0));
+        // Stable when applied again
+        assert_eq!(c.apply(size, ResizeAxis::Height), size);
+    }
+
+    #[test]
+    fn test_increments_stay_above_min() {
+        let c = ResizeConstraints {
+            increment: Some(ResizeIncrement {
+                step: Size::new(30.0, 30.0),
+                base: Size::ZERO,
+            }),
+            ..constraints()
+        };
+        // 210 is the first multiple of 30 at or above 200
+        let size = c.apply(Size::new(100.0, 100.0), ResizeAxis::Width);
+        assert_eq!(size, Size::new(210.0, 150.0));
+    }
+
+    #[test]
+    fn test_dominant_axis() {
+        let from = Size::new(800.0, 600.0);
+        assert_eq!(
+            ResizeAxis::dominant(from, Size::new(900.0, 610.0)),
+            ResizeAxis::Width
+        );
+        assert_eq!(
+            ResizeAxis::dominant(from, Size::new(810.0, 700.0)),
+            ResizeAxis::Height
+        );
+    }
+}
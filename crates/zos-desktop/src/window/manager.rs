@@ -11,9 +11,11 @@
 //!
 //! - Operations on non-existent windows are no-ops (silently ignored)
 //! - Hit testing returns None for positions outside all windows
-//! - Resize respects min/max size constraints
+//! - Resize respects min/max size, aspect-ratio and increment constraints
 
-use super::{Window, WindowConfig, WindowId, WindowRegion, WindowState};
+use super::{
+    ResizeAxis, ResizeIncrement, Window, WindowConfig, WindowId, WindowRegion, WindowState,
+};
 use crate::math::{Rect, Size, Vec2, FRAME_STYLE};
 use std::collections::HashMap;
 
@@ -68,12 +70,15 @@ impl WindowManager {
             size: config.size,
             min_size: config.min_size.unwrap_or(Size::new(200.0, 150.0)),
             max_size: config.max_size,
+            aspect_ratio: config.aspect_ratio,
+            resize_increment: config.resize_increment,
             state: WindowState::Normal,
             window_type: config.window_type,
             process_id: config.process_id,
             z_order,
             restore_rect: None,
             prev_state: None,
+            fullscreen_restore: None,
             content_interactive: config.content_interactive,
         };
 
@@ -114,6 +119,14 @@ impl WindowManager {
         }
     }
 
+    /// Bring a window to the top of the z-order without focusing it
+    pub fn raise(&mut self, id: WindowId) {
+        if let Some(window) = self.windows.get_mut(&id) {
+            window.z_order = self.next_z;
+            self.next_z += 1;
+        }
+    }
+
     /// Get the currently focused window ID
     pub fn focused(&self) -> Option<WindowId> {
         for &id in self.focus_stack.iter().rev() {
@@ -127,24 +140,42 @@ impl WindowManager {
     }
 
     /// Move a window to a new position
+    ///
+    /// Fullscreen windows stay pinned to the screen.
     pub fn move_window(&mut self, id: WindowId, position: Vec2) {
         if let Some(window) = self.windows.get_mut(&id) {
-            window.position = position;
+            if !window.is_fullscreen() {
+                window.position = position;
+            }
         }
     }
 
     /// Resize a window
     pub fn resize(&mut self, id: WindowId, size: Size) {
         if let Some(window) = self.windows.get_mut(&id) {
-            let mut width = size.width.max(window.min_size.width);
-            let mut height = size.height.max(window.min_size.height);
-
-            if let Some(max) = window.max_size {
-                width = width.min(max.width);
-                height = height.min(max.height);
+            if window.is_fullscreen() {
+                return;
             }
+            let axis = ResizeAxis::dominant(window.size, size);
+            window.size = window.resize_constraints().apply(size, axis);
+        }
+    }
 
-            window.size = Size::new(width, height);
+    /// Replace a window's aspect-ratio lock and resize increments
+    ///
+    /// The current size is re-constrained immediately.
+    pub fn set_resize_constraints(
+        &mut self,
+        id: WindowId,
+        aspect_ratio: Option<f32>,
+        increment: Option<ResizeIncrement>,
+    ) {
+        if let Some(window) = self.windows.get_mut(&id) {
+            window.aspect_ratio = aspect_ratio;
+            window.resize_increment = increment;
+            window.size = window
+                .resize_constraints()
+                .apply(window.size, ResizeAxis::Width);
         }
     }
 
@@ -166,8 +197,13 @@ impl WindowManager {
     }
 
     /// Maximize a window (or restore if already maximized)
+    ///
+    /// Fullscreen windows are left alone; leave fullscreen first.
     pub fn maximize(&mut self, id: WindowId, bounds: Option<Rect>) {
         if let Some(window) = self.windows.get_mut(&id) {
+            if window.is_fullscreen() {
+                return;
+            }
             if window.state == WindowState::Maximized {
                 // Restore
                 window.state = WindowState::Normal;
@@ -188,6 +224,60 @@ impl WindowManager {
         }
     }
 
+    /// Make a window cover `bounds` without a frame
+    ///
+    /// The current state, position and size are saved for
+    /// [`exit_fullscreen`](Self::exit_fullscreen). Calling this on a window
+    /// that is already fullscreen only moves it to the new bounds (e.g.
+    /// after the screen is resized). Minimized windows are ignored.
+    pub fn enter_fullscreen(&mut self, id: WindowId, bounds: Rect) {
+        if let Some(window) = self.windows.get_mut(&id) {
+            match window.state {
+                WindowState::Minimized => return,
+                WindowState::Fullscreen => {}
+                state => {
+                    window.fullscreen_restore = Some((state, window.position, window.size));
+                    window.state = WindowState::Fullscreen;
+                }
+            }
+            window.position = bounds.position();
+            window.size = bounds.size();
+        }
+    }
+
+    /// Return a fullscreen window to the state it had before
+    ///
+    /// A maximized window goes back to being maximized, keeping its
+    /// original restore rect. Returns false if the window was not fullscreen.
+    pub fn exit_fullscreen(&mut self, id: WindowId) -> bool {
+        let window = match self.windows.get_mut(&id) {
+            Some(window) => window,
+            None => return false,
+        };
+        // A minimized fullscreen window comes back to normal, not fullscreen
+        if window.state == WindowState::Minimized
+            && window.prev_state == Some(WindowState::Fullscreen)
+        {
+            window.prev_state = None;
+        } else if window.state != WindowState::Fullscreen {
+            return false;
+        }
+
+        let (state, position, size) = window.fullscreen_restore.take().unwrap_or((
+            WindowState::Normal,
+            window.position,
+            window.size,
+        ));
+        if window.state == WindowState::Fullscreen {
+            window.state = state;
+        } else {
+            window.prev_state = Some(state);
+        }
+        window.position = position;
+        window.size = size;
+        true
+    }
+
     /// Restore a minimized window
     pub fn restore(&mut self, id: WindowId) {
         if let Some(window) = self.windows.get_mut(&id) {
@@ -270,14 +360,23 @@ impl WindowManager {
 
     /// Hit test a specific window at a position
     fn hit_test_window(&self, window: &Window, pos: Vec2, zoom: f32) -> Option<WindowRegion> {
+        // Fullscreen windows have no frame: everything is content
+        if window.is_fullscreen() {
+            return Some(WindowRegion::Content);
+        }
+
         // Check buttons first (highest priority)
         if let Some(region) = hit_test_buttons(window, pos) {
             return Some(region);
         }
 
+        let resizable = window.is_resizable();
+
         // Check resize corners (before title bar to allow corner grabs)
-        if let Some(region) = hit_test_resize_corners(window, pos, zoom) {
-            return Some(region);
+        if resizable {
+            if let Some(region) = hit_test_resize_corners(window, pos, zoom) {
+                return Some(region);
+            }
         }
 
         // Check title bar
@@ -286,8 +385,10 @@ impl WindowManager {
         }
 
         // Check resize edges
-        if let Some(region) = hit_test_resize_edges(window, pos, zoom) {
-            return Some(region);
+        if resizable {
+            if let Some(region) = hit_test_resize_edges(window, pos, zoom) {
+                return Some(region);
+            }
         }
 
         // Default to content
@@ -484,6 +585,62 @@ mod tests {
         assert!((window.size.width - 800.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_fullscreen_restores_maximized() {
+        let mut wm = WindowManager::new();
+        let id = wm.create(WindowConfig {
+            position: Some(Vec2::new(100.0, 100.0)),
+            size: Size::new(800.0, 600.0),
+            ..Default::default()
+        });
+        let maximized = Rect::new(0.0, 0.0, 1920.0, 1032.0);
+        let screen = Rect::new(0.0, 0.0, 1920.0, 1080.0);
+        wm.maximize(id, Some(maximized));
+
+        wm.enter_fullscreen(id, screen);
+        let window = wm.get(id).unwrap();
+        assert_eq!(window.state, WindowState::Fullscreen);
+        assert_eq!(window.rect(), screen);
+        assert_eq!(
+            wm.region_at(Vec2::new(5.0, 5.0)),
+            Some((id, WindowRegion::Content))
+        );
+
+        // Pinned while fullscreen
+        wm.move_window(id, Vec2::new(500.0, 500.0));
+        wm.resize(id, Size::new(300.0, 300.0));
+        assert_eq!(wm.get(id).unwrap().rect(), screen);
+
+        assert!(wm.exit_fullscreen(id));
+        assert_eq!(wm.get(id).unwrap().state, WindowState::Maximized);
+        assert_eq!(wm.get(id).unwrap().rect(), maximized);
+        assert!(!wm.exit_fullscreen(id));
+
+        // Un-maximizing still returns to the original rect
+        wm.maximize(id, Some(maximized));
+        let window = wm.get(id).unwrap();
+        assert_eq!(window.rect(), Rect::new(100.0, 100.0, 800.0, 600.0));
+    }
+
+    #[test]
+    fn test_minimized_fullscreen_exit() {
+        let mut wm = WindowManager::new();
+        let id = wm.create(WindowConfig {
+            position: Some(Vec2::new(100.0, 100.0)),
+            size: Size::new(800.0, 600.0),
+            ..Default::default()
+        });
+        wm.enter_fullscreen(id, Rect::new(0.0, 0.0, 1920.0, 1080.0));
+        wm.minimize(id);
+
+        assert!(wm.exit_fullscreen(id));
+        assert_eq!(wm.get(id).unwrap().state, WindowState::Minimized);
+        wm.restore(id);
+        let window = wm.get(id).unwrap();
+        assert_eq!(window.state, WindowState::Normal);
+        assert_eq!(window.size, Size::new(800.0, 600.0));
+    }
+
     #[test]
     fn test_hit_testing() {
         let mut wm = WindowManager::new();
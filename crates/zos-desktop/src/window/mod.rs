@@ -3,11 +3,13 @@
 //! Provides window lifecycle, focus management, and hit testing.
 
 mod config;
+mod constraints;
 mod manager;
 mod region;
 mod types;
 
 pub use config::WindowConfig;
+pub use constraints::{ResizeAxis, ResizeConstraints, ResizeIncrement};
 pub use manager::WindowManager;
 pub use region::WindowRegion;
 pub use types::{Window, WindowState, WindowType};
@@ -1,6 +1,6 @@
 //! Window struct and state
 
-use super::WindowId;
+use super::{ResizeConstraints, ResizeIncrement, WindowId};
 use crate::math::{Rect, Size, Vec2, FRAME_STYLE};
 use serde::{Deserialize, Serialize};
 
@@ -43,6 +43,10 @@ pub struct Window {
     pub min_size: Size,
     /// Maximum size (None = no limit)
     pub max_size: Option<Size>,
+    /// Locked width / height ratio
+    pub aspect_ratio: Option<f32>,
+    /// Size steps (e.g. terminal cells)
+    pub resize_increment: Option<ResizeIncrement>,
     /// Current state
     pub state: WindowState,
     /// Window type (standard or widget)
@@ -55,6 +59,8 @@ pub struct Window {
     pub(crate) restore_rect: Option<(Vec2, Size)>,
     /// Previous state before minimize
     pub(crate) prev_state: Option<WindowState>,
+    /// State, position and size to return to when leaving fullscreen
+    pub(crate) fullscreen_restore: Option<(WindowState, Vec2, Size)>,
     /// Whether the window content area handles its own mouse events
     pub content_interactive: bool,
 }
@@ -66,6 +72,30 @@ impl Window {
         Rect::from_pos_size(self.position, self.size)
     }
 
+    /// Whether the user can resize the window
+    ///
+    /// A window whose maximum size equals its minimum size is locked.
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        self.max_size != Some(self.min_size)
+    }
+
+    /// Whether the window covers the whole screen without a frame
+    #[inline]
+    pub fn is_fullscreen(&self) -> bool {
+        self.state == WindowState::Fullscreen
+    }
+
+    /// All constraints limiting this window's size
+    pub fn resize_constraints(&self) -> ResizeConstraints {
+        ResizeConstraints {
+            min_size: self.min_size,
+            max_size: self.max_size,
+            aspect_ratio: self.aspect_ratio,
+            increment: self.resize_increment,
+        }
+    }
+
     /// Get the title bar rectangle
     pub fn title_bar_rect(&self) -> Rect {
         Rect::new(
@@ -77,7 +107,12 @@ impl Window {
     }
 
     /// Get the content area rectangle (excludes title bar)
+    ///
+    /// Fullscreen windows have no title bar, so content fills the window.
     pub fn content_rect(&self) -> Rect {
+        if self.is_fullscreen() {
+            return self.rect();
+        }
         Rect::new(
             self.position.x,
             self.position.y + FRAME_STYLE.title_bar_height,
@@ -128,12 +163,15 @@ mod tests {
             size: Size::new(800.0, 600.0),
             min_size: Size::new(200.0, 150.0),
             max_size: None,
+            aspect_ratio: None,
+            resize_increment: None,
             state: WindowState::Normal,
             window_type: WindowType::Standard,
             process_id: None,
             z_order: 1,
             restore_rect: None,
             prev_state: None,
+            fullscreen_restore: None,
             content_interactive: false,
         }
     }
@@ -164,4 +202,11 @@ mod tests {
         let r = w.content_rect();
         assert!((r.y - (100.0 + FRAME_STYLE.title_bar_height)).abs() < 0.001);
     }
+
+    #[test]
+    fn test_fullscreen_content_fills_window() {
+        let mut w = create_test_window();
+        w.state = WindowState::Fullscreen;
+        assert_eq!(w.content_rect(), w.rect());
+    }
 }
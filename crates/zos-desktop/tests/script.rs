@@ -0,0 +1,220 @@
+//! End-to-end window manager tests driven by automation scripts
+//!
+//! Every test runs against a fresh engine with a virtual clock, so results
+//! are identical on every run and no browser is needed.
+
+use zos_desktop::script::ScriptOutput;
+use zos_desktop::{ScriptOp, ScriptRunner, WindowState, CROSSFADE_DURATION_MS};
+
+fn window(title: &str, x: f32, y: f32) -> ScriptOp {
+    ScriptOp::CreateWindow {
+        title: title.to_string(),
+        app_id: "test".to_string(),
+        x: Some(x),
+        y: Some(y),
+        width: 400.0,
+        height: 300.0,
+    }
+}
+
+fn created_id(output: &ScriptOutput) -> u64 {
+    match output {
+        ScriptOutput::Window { id } => *id,
+        other => panic!("expected a window, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_script_window_lifecycle() {
+    let mut runner = ScriptRunner::new(1920.0, 1080.0);
+    let report = runner.run(&[window("A", 0.0, 0.0), window("B", 100.0, 100.0)]);
+    assert!(report.is_ok());
+    let a = created_id(&report.outputs[0]);
+    let b = created_id(&report.outputs[1]);
+
+    let report = runner.run(&[
+        ScriptOp::FocusWindow { window: a },
+        ScriptOp::MoveWindow {
+            window: a,
+            x: 300.0,
+            y: 200.0,
+        },
+        ScriptOp::MinimizeWindow { window: b },
+        ScriptOp::Query,
+    ]);
+    assert!(report.is_ok(), "{:?}", report.failure);
+
+    let state = report.last_state().unwrap();
+    let wa = state.window(a).unwrap();
+    assert!(wa.focused);
+    assert_eq!((wa.x, wa.y), (300.0, 200.0));
+    assert_eq!(state.window(b).unwrap().state, WindowState::Minimized);
+    // Focused window is on top
+    assert_eq!(state.windows.last().unwrap().id, a);
+}
+
+#[test]
+fn test_script_desktop_switch_preserves_windows() {
+    let mut runner = ScriptRunner::new(1920.0, 1080.0);
+    let report = runner.run(&[
+        window("Main window", 0.0, 0.0),
+        ScriptOp::CreateDesktop {
+            name: "Second".to_string(),
+        },
+        ScriptOp::SwitchDesktop { index: 1 },
+        ScriptOp::Query,
+        ScriptOp::Settle,
+        window("Second window", 0.0, 0.0),
+        ScriptOp::Query,
+    ]);
+    assert!(report.is_ok(), "{:?}", report.failure);
+    let first = created_id(&report.outputs[0]);
+    let second = created_id(&report.outputs[5]);
+
+    let ScriptOutput::State(during) = &report.outputs[3] else {
+        panic!("expected state");
+    };
+    assert!(during.transitioning);
+
+    let after = report.last_state().unwrap();
+    assert!(!after.transitioning);
+    assert_eq!(after.active_desktop, 1);
+    assert_eq!(after.desktops[0].windows, vec![first]);
+    assert_eq!(after.desktops[1].windows, vec![second]);
+    assert_eq!(after.window(first).unwrap().desktop, Some(0));
+}
+
+#[test]
+fn test_script_void_round_trip() {
+    let mut runner = ScriptRunner::new(1920.0, 1080.0);
+    let report = runner.run(&[
+        ScriptOp::CreateDesktop {
+            name: "Second".to_string(),
+        },
+        ScriptOp::EnterVoid,
+        ScriptOp::Advance {
+            ms: CROSSFADE_DURATION_MS as f64,
+        },
+        ScriptOp::Query,
+        ScriptOp::ExitVoid { index: 1 },
+        ScriptOp::Settle,
+        ScriptOp::Query,
+    ]);
+    assert!(report.is_ok(), "{:?}", report.failure);
+
+    let ScriptOutput::State(in_void) = &report.outputs[3] else {
+        panic!("expected state");
+    };
+    assert_eq!(in_void.view, "void");
+    let back = report.last_state().unwrap();
+    assert_eq!(back.view, "desktop");
+    assert_eq!(back.active_desktop, 1);
+}
+
+#[test]
+fn test_script_drag_moves_window() {
+    let mut runner = ScriptRunner::new(1920.0, 1080.0);
+    let report = runner.run(&[window("Drag me", 0.0, 0.0), ScriptOp::Query]);
+    let id = created_id(&report.outputs[0]);
+    let before = report.last_state().unwrap().clone();
+    let w = before.window(id).unwrap();
+
+    // Grab the title bar (canvas -> screen at zoom 1 with the camera centered)
+    let grab_x = w.x + w.width / 2.0 - before.camera_x + 960.0;
+    let grab_y = w.y + 10.0 - before.camera_y + 540.0;
+    let report = runner.run(&[
+        ScriptOp::PointerDown {
+            x: grab_x,
+            y: grab_y,
+            button: 0,
+            ctrl: false,
+            shift: false,
+        },
+        ScriptOp::PointerMove {
+            x: grab_x + 120.0,
+            y: grab_y + 80.0,
+        },
+        ScriptOp::PointerUp,
+        ScriptOp::Query,
+    ]);
+    assert!(report.is_ok(), "{:?}", report.failure);
+
+    let moved = report.last_state().unwrap().window(id).unwrap().clone();
+    assert!((moved.x - (w.x + 120.0)).abs() < 0.01);
+    assert!((moved.y - (w.y + 80.0)).abs() < 0.01);
+}
+
+#[test]
+fn test_script_stops_at_first_failure() {
+    let mut runner = ScriptRunner::new(1920.0, 1080.0);
+    let report = runner.run(&[
+        window("A", 0.0, 0.0),
+        ScriptOp::CloseWindow { window: 999 },
+        ScriptOp::CreateDesktop {
+            name: "Never".to_string(),
+        },
+    ]);
+    let failure = report.failure.as_ref().unwrap();
+    assert_eq!(failure.step, 1);
+    assert_eq!(failure.message, "window not found: 999");
+    assert_eq!(report.outputs.len(), 1);
+    assert_eq!(runner.state().desktops.len(), 1);
+
+    let report = runner.run(&[ScriptOp::SwitchDesktop { index: 4 }]);
+    assert_eq!(
+        report.failure.unwrap().message,
+        "desktop index 4 out of bounds (count: 1)"
+    );
+
+    let report = runner.run(&[ScriptOp::Advance { ms: -1.0 }]);
+    assert!(!report.is_ok());
+}
+
+#[test]
+fn test_script_clock_is_virtual_and_deterministic() {
+    let script = [
+        window("A", 0.0, 0.0),
+        ScriptOp::CreateDesktop {
+            name: "Second".to_string(),
+        },
+        ScriptOp::SwitchDesktop { index: 1 },
+        ScriptOp::Advance { ms: 100.0 },
+        ScriptOp::Query,
+        ScriptOp::Settle,
+        ScriptOp::Query,
+    ];
+
+    let mut first = ScriptRunner::new(1920.0, 1080.0);
+    let mut second = ScriptRunner::new(1920.0, 1080.0);
+    let a = first.run(&script);
+    let b = second.run(&script);
+    assert_eq!(a, b);
+
+    let ScriptOutput::State(mid) = &a.outputs[4] else {
+        panic!("expected state");
+    };
+    assert_eq!(mid.now_ms, 100.0);
+    assert!(a.now_ms > 100.0);
+    assert_eq!(first.now_ms(), a.now_ms);
+}
+
+#[test]
+fn test_script_json_round_trip() {
+    let json = r#"[
+        {"op": "create_window", "title": "Notes", "app_id": "notes", "width": 640, "height": 480},
+        {"op": "enter_void"},
+        {"op": "settle"},
+        {"op": "query"}
+    ]"#;
+    let ops: Vec<ScriptOp> = serde_json::from_str(json).unwrap();
+    assert_eq!(ops.len(), 4);
+
+    let mut runner = ScriptRunner::new(1920.0, 1080.0);
+    let report = runner.run(&ops);
+    let encoded = serde_json::to_string(&report).unwrap();
+    assert!(encoded.contains(r#""view":"void""#));
+    assert!(encoded.contains(r#""type":"window""#));
+
+    let decoded: zos_desktop::ScriptReport = serde_json::from_str(&encoded).unwrap();
+    assert_eq!(decoded, report);
+}
@@ -0,0 +1,441 @@
+//! Feature flags for Zero OS
+//!
+//! This crate provides the flag model shared by the FeatureFlagService, the
+//! supervisor and any service that wants to gate a risky subsystem (new
+//! allocator, new storage backend) behind a runtime toggle.
+//!
+//! # Resolution
+//!
+//! A flag's effective value is resolved in order, first match wins:
+//!
+//! 1. Per-user override (if a user is given)
+//! 2. System-wide value
+//! 3. Built-in default from [`KNOWN_FLAGS`]
+//! 4. `false`
+//!
+//! # Usage
+//!
+//! ```
+//! use zos_flags::{known, FlagStore};
+//!
+//! let mut store = FlagStore::default();
+//! store.set_system(known::VFS_JOURNAL, Some(true)).unwrap();
+//!
+//! let flags = store.resolve(None);
+//! assert!(flags.is_enabled(known::VFS_JOURNAL));
+//! ```
+//!
+//! Flags are persisted as JSON at [`FLAGS_PATH`]. Services may read that file
+//! via VFS at startup and resolve it locally; changes go through the
+//! FeatureFlagService so they are validated and broadcast.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// VFS path of the persisted flag store.
+pub const FLAGS_PATH: &str = "/system/config/flags.json";
+
+/// Maximum flag name length in bytes.
+pub const MAX_FLAG_NAME_LEN: usize = 64;
+
+/// Maximum number of flags stored per scope (system or one user).
+pub const MAX_FLAGS_PER_SCOPE: usize = 128;
+
+/// Maximum number of users with overrides.
+pub const MAX_USERS_WITH_OVERRIDES: usize = 64;
+
+// =============================================================================
+// Well-known Flags
+// =============================================================================
+
+/// Names of flags consulted by system components.
+pub mod known {
+    /// Journal VFS metadata writes before applying them.
+    pub const VFS_JOURNAL: &str = "vfs.journal";
+    /// Check file content against its inode's hash on every whole-file read.
+    pub const VFS_VERIFY_HASHES: &str = "vfs.verify_hashes";
+    /// Use the next-generation kernel allocator.
+    pub const KERNEL_ALLOCATOR_V2: &str = "kernel.allocator_v2";
+    /// Use the next-generation storage backend.
+    pub const STORAGE_BACKEND_V2: &str = "storage.backend_v2";
+    /// Fault injection: delay or drop storage results.
+    pub const CHAOS_STORAGE_FAULTS: &str = "chaos.storage_faults";
+    /// Fault injection: fail IPC sends with transient errors.
+    pub const CHAOS_IPC_FAULTS: &str = "chaos.ipc_faults";
+    /// Fault injection: kill random non-core processes.
+    pub const CHAOS_PROCESS_KILLS: &str = "chaos.process_kills";
+    /// Accept desktop automation scripts from processes.
+    pub const DESKTOP_AUTOMATION: &str = "desktop.automation";
+    /// Speak the newly focused window through the speech service.
+    pub const ANNOUNCE_FOCUS: &str = "accessibility.announce_focus";
+}
+
+/// A flag the system knows about, with its built-in default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlagDef {
+    /// Flag name
+    pub name: &'static str,
+    /// Value when neither system nor user sets it
+    pub default: bool,
+    /// Short description for settings UIs
+    pub description: &'static str,
+}
+
+/// Built-in flag definitions.
+pub static KNOWN_FLAGS: &[FlagDef] = &[
+    FlagDef {
+        name: known::VFS_JOURNAL,
+        default: false,
+        description: "Journal VFS metadata writes",
+    },
+    FlagDef {
+        name: known::VFS_VERIFY_HASHES,
+        default: false,
+        description: "Verify file content against its stored hash on read",
+    },
+    FlagDef {
+        name: known::KERNEL_ALLOCATOR_V2,
+        default: false,
+        description: "Next-generation kernel allocator",
+    },
+    FlagDef {
+        name: known::STORAGE_BACKEND_V2,
+        default: false,
+        description: "Next-generation storage backend",
+    },
+    FlagDef {
+        name: known::CHAOS_STORAGE_FAULTS,
+        default: false,
+        description: "Fault injection: delay or drop storage results",
+    },
+    FlagDef {
+        name: known::CHAOS_IPC_FAULTS,
+        default: false,
+        description: "Fault injection: fail IPC sends with transient errors",
+    },
+    FlagDef {
+        name: known::CHAOS_PROCESS_KILLS,
+        default: false,
+        description: "Fault injection: kill random non-core processes",
+    },
+    FlagDef {
+        name: known::DESKTOP_AUTOMATION,
+        default: false,
+        description: "Let processes drive the desktop with automation scripts",
+    },
+    FlagDef {
+        name: known::ANNOUNCE_FOCUS,
+        default: false,
+        description: "Announce focus changes with the screen reader voice",
+    },
+];
+
+/// Look up a built-in flag definition.
+pub fn known_flag(name: &str) -> Option<&'static FlagDef> {
+    KNOWN_FLAGS.iter().find(|f| f.name == name)
+}
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// Errors from flag store mutations.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlagError {
+    /// Flag name is empty, too long, or contains invalid characters
+    InvalidName(String),
+    /// Scope already holds `MAX_FLAGS_PER_SCOPE` flags
+    TooManyFlags,
+    /// Store already holds overrides for `MAX_USERS_WITH_OVERRIDES` users
+    TooManyUsers,
+}
+
+impl FlagError {
+    /// Human-readable error message.
+    pub fn message(&self) -> String {
+        match self {
+            FlagError::InvalidName(name) => format!("Invalid flag name: {:?}", name),
+            FlagError::TooManyFlags => format!("Flag limit reached ({})", MAX_FLAGS_PER_SCOPE),
+            FlagError::TooManyUsers => {
+                format!("User override limit reached ({})", MAX_USERS_WITH_OVERRIDES)
+            }
+        }
+    }
+}
+
+/// Validate a flag name: lowercase dotted identifiers like `vfs.journal`.
+pub fn validate_name(name: &str) -> Result<(), FlagError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_FLAG_NAME_LEN
+        && !name.starts_with('.')
+        && !name.ends_with('.')
+        && !name.contains("..")
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'.' || b == b'_');
+    if valid {
+        Ok(())
+    } else {
+        Err(FlagError::InvalidName(String::from(name)))
+    }
+}
+
+/// Key used for a user's overrides in the persisted JSON.
+fn user_key(user_id: u128) -> String {
+    format!("{:032x}", user_id)
+}
+
+// =============================================================================
+// Flag Store (persisted)
+// =============================================================================
+
+/// Persisted flag values: system-wide plus per-user overrides.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlagStore {
+    /// System-wide values
+    #[serde(default)]
+    pub system: BTreeMap<String, bool>,
+    /// Per-user overrides, keyed by 32-char hex user ID
+    #[serde(default)]
+    pub users: BTreeMap<String, BTreeMap<String, bool>>,
+}
+
+impl FlagStore {
+    /// Serialize to JSON bytes.
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes.
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+
+    /// Set (`Some`) or clear (`None`) a system-wide value.
+    pub fn set_system(&mut self, name: &str, value: Option<bool>) -> Result<(), FlagError> {
+        validate_name(name)?;
+        set_in(&mut self.system, name, value)
+    }
+
+    /// Set (`Some`) or clear (`None`) a per-user override.
+    pub fn set_user(
+        &mut self,
+        user_id: u128,
+        name: &str,
+        value: Option<bool>,
+    ) -> Result<(), FlagError> {
+        validate_name(name)?;
+        let key = user_key(user_id);
+        if !self.users.contains_key(&key) {
+            if value.is_none() {
+                return Ok(());
+            }
+            if self.users.len() >= MAX_USERS_WITH_OVERRIDES {
+                return Err(FlagError::TooManyUsers);
+            }
+        }
+        let overrides = self.users.entry(key.clone()).or_default();
+        set_in(overrides, name, value)?;
+        if overrides.is_empty() {
+            self.users.remove(&key);
+        }
+        Ok(())
+    }
+
+    /// Resolve effective values for a user (or system-wide with `None`).
+    pub fn resolve(&self, user_id: Option<u128>) -> FeatureFlags {
+        let mut flags: BTreeMap<String, bool> = KNOWN_FLAGS
+            .iter()
+            .map(|f| (String::from(f.name), f.default))
+            .collect();
+        for (name, value) in &self.system {
+            flags.insert(name.clone(), *value);
+        }
+        if let Some(overrides) = user_id.and_then(|id| self.users.get(&user_key(id))) {
+            for (name, value) in overrides {
+                flags.insert(name.clone(), *value);
+            }
+        }
+        FeatureFlags { flags }
+    }
+}
+
+fn set_in(
+    scope: &mut BTreeMap<String, bool>,
+    name: &str,
+    value: Option<bool>,
+) -> Result<(), FlagError> {
+    match value {
+        Some(v) => {
+            if !scope.contains_key(name) && scope.len() >= MAX_FLAGS_PER_SCOPE {
+                return Err(FlagError::TooManyFlags);
+            }
+            scope.insert(String::from(name), v);
+        }
+        None => {
+            scope.remove(name);
+        }
+    }
+    Ok(())
+}
+
+// =============================================================================
+// Resolved Snapshot
+// =============================================================================
+
+/// Resolved flag values for one scope - the typed query API.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureFlags {
+    /// Effective values, including built-in defaults
+    pub flags: BTreeMap<String, bool>,
+}
+
+impl FeatureFlags {
+    /// Whether a flag is enabled. Unknown flags are disabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        match self.flags.get(name) {
+            Some(v) => *v,
+            None => known_flag(name).is_some_and(|f| f.default),
+        }
+    }
+
+    /// Serialize to JSON bytes.
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes.
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: u128 = 0xA11CE;
+    const BOB: u128 = 0xB0B;
+
+    #[test]
+    fn test_unknown_flags_are_disabled() {
+        let flags = FlagStore::default().resolve(None);
+        assert!(!flags.is_enabled("no.such.flag"));
+        assert!(!FeatureFlags::default().is_enabled(known::VFS_JOURNAL));
+    }
+
+    #[test]
+    fn test_known_defaults_are_resolved() {
+        let flags = FlagStore::default().resolve(None);
+        for def in KNOWN_FLAGS {
+            assert_eq!(flags.flags.get(def.name), Some(&def.default));
+        }
+    }
+
+    #[test]
+    fn test_user_override_beats_system() {
+        let mut store = FlagStore::default();
+        store.set_system(known::VFS_JOURNAL, Some(true)).unwrap();
+        store
+            .set_user(ALICE, known::VFS_JOURNAL, Some(false))
+            .unwrap();
+
+        assert!(store.resolve(None).is_enabled(known::VFS_JOURNAL));
+        assert!(!store.resolve(Some(ALICE)).is_enabled(known::VFS_JOURNAL));
+        assert!(store.resolve(Some(BOB)).is_enabled(known::VFS_JOURNAL));
+    }
+
+    #[test]
+    fn test_clearing_last_override_drops_user() {
+        let mut store = FlagStore::default();
+        store.set_user(ALICE, "ui.beta", Some(true)).unwrap();
+        store.set_user(ALICE, "ui.beta", None).unwrap();
+        assert!(store.users.is_empty());
+
+        // Clearing for a user with no overrides is a no-op
+        store.set_user(BOB, "ui.beta", None).unwrap();
+        assert!(store.users.is_empty());
+    }
+
+    #[test]
+    fn test_clearing_system_value_restores_default() {
+        let mut store = FlagStore::default();
+        store
+            .set_system(known::STORAGE_BACKEND_V2, Some(true))
+            .unwrap();
+        store.set_system(known::STORAGE_BACKEND_V2, None).unwrap();
+        assert!(!store.resolve(None).is_enabled(known::STORAGE_BACKEND_V2));
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("vfs.journal").is_ok());
+        assert!(validate_name("kernel.allocator_v2").is_ok());
+        for bad in [
+            "",
+            ".vfs",
+            "vfs.",
+            "vfs..journal",
+            "VFS.journal",
+            "vfs/journal",
+            "vfs journal",
+        ] {
+            assert!(validate_name(bad).is_err(), "{:?} should be rejected", bad);
+        }
+        let long = "a".repeat(MAX_FLAG_NAME_LEN + 1);
+        assert!(validate_name(&long).is_err());
+    }
+
+    #[test]
+    fn test_scope_limit() {
+        let mut store = FlagStore::default();
+        for i in 0..MAX_FLAGS_PER_SCOPE {
+            store.set_system(&format!("f.{}", i), Some(true)).unwrap();
+        }
+        assert_eq!(
+            store.set_system("f.overflow", Some(true)),
+            Err(FlagError::TooManyFlags)
+        );
+        // Updating an existing flag is still allowed
+        assert!(store.set_system("f.0", Some(false)).is_ok());
+    }
+
+    #[test]
+    fn test_user_limit() {
+        let mut store = FlagStore::default();
+        for user in 0..MAX_USERS_WITH_OVERRIDES as u128 {
+            store.set_user(user, "ui.beta", Some(true)).unwrap();
+        }
+        assert_eq!(
+            store.set_user(u128::MAX, "ui.beta", Some(true)),
+            Err(FlagError::TooManyUsers)
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut store = FlagStore::default();
+        store.set_system(known::VFS_JOURNAL, Some(true)).unwrap();
+        store.set_user(ALICE, "ui.beta", Some(true)).unwrap();
+
+        let parsed = FlagStore::from_json(&store.to_json()).unwrap();
+        assert_eq!(parsed, store);
+
+        let flags = parsed.resolve(Some(ALICE));
+        let parsed_flags = FeatureFlags::from_json(&flags.to_json()).unwrap();
+        assert!(parsed_flags.is_enabled("ui.beta"));
+    }
+
+    #[test]
+    fn test_from_empty_json_object() {
+        let store = FlagStore::from_json(b"{}").unwrap();
+        assert_eq!(store, FlagStore::default());
+    }
+}
@@ -0,0 +1,231 @@
+//! Async I/O submission and completion
+//!
+//! Storage, keystore and network operations all follow the same shape: the
+//! kernel submits an [`AsyncOp`] on behalf of a process and gets a request
+//! ID back straight away; the platform later reports an [`AsyncCompletion`]
+//! carrying that ID, which the kernel hands back to the process.
+//!
+//! How the work is done is up to the platform. The browser forwards it to
+//! IndexedDB and `fetch()` and queues completions from their JavaScript
+//! callbacks; the x86_64 HAL does storage synchronously and queues the
+//! completion before returning the request ID. Either way the kernel
+//! collects them with [`HAL::poll_async_completions`](crate::HAL::poll_async_completions).
+//!
+//! An operation the platform never finishes would leave its process waiting
+//! forever. [`HAL::pending_async_ops`](crate::HAL::pending_async_ops) lists
+//! what is still outstanding, and [`HAL::fail_async`](crate::HAL::fail_async)
+//! completes one with an error in its place.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Which backend an async operation runs against
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsyncChannel {
+    /// Platform storage (VFS content and inodes)
+    Storage,
+    /// Dedicated keystore (key material only)
+    Keystore,
+    /// HTTP fetch
+    Network,
+}
+
+/// An async operation submitted on behalf of a process
+#[derive(Clone, Copy, Debug)]
+pub enum AsyncOp<'a> {
+    StorageRead {
+        key: &'a str,
+    },
+    StorageWrite {
+        key: &'a str,
+        value: &'a [u8],
+    },
+    StorageDelete {
+        key: &'a str,
+    },
+    StorageList {
+        prefix: &'a str,
+    },
+    StorageExists {
+        key: &'a str,
+    },
+    /// Several writes committed together
+    StorageBatchWrite {
+        items: &'a [(&'a str, &'a [u8])],
+    },
+    KeystoreRead {
+        key: &'a str,
+    },
+    KeystoreWrite {
+        key: &'a str,
+        value: &'a [u8],
+    },
+    KeystoreDelete {
+        key: &'a str,
+    },
+    KeystoreList {
+        prefix: &'a str,
+    },
+    KeystoreExists {
+        key: &'a str,
+    },
+    /// Serialized HttpRequest (JSON bytes)
+    NetworkFetch {
+        request: &'a [u8],
+    },
+}
+
+impl AsyncOp<'_> {
+    /// The backend this operation runs against.
+    pub fn channel(&self) -> AsyncChannel {
+        match self {
+            AsyncOp::StorageRead { .. }
+            | AsyncOp::StorageWrite { .. }
+            | AsyncOp::StorageDelete { .. }
+            | AsyncOp::StorageList { .. }
+            | AsyncOp::StorageExists { .. }
+            | AsyncOp::StorageBatchWrite { .. } => AsyncChannel::Storage,
+            AsyncOp::KeystoreRead { .. }
+            | AsyncOp::KeystoreWrite { .. }
+            | AsyncOp::KeystoreDelete { .. }
+            | AsyncOp::KeystoreList { .. }
+            | AsyncOp::KeystoreExists { .. } => AsyncChannel::Keystore,
+            AsyncOp::NetworkFetch { .. } => AsyncChannel::Network,
+        }
+    }
+}
+
+/// How an async operation ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AsyncOutcome {
+    /// Read found the key
+    ReadOk(Vec<u8>),
+    /// Read found nothing under the key
+    NotFound,
+    /// Write, delete or batch write committed
+    WriteOk,
+    /// Keys matching the prefix, as a JSON array of strings
+    ListOk(Vec<u8>),
+    /// Whether the key exists
+    ExistsOk(bool),
+    /// The backend failed; the message is safe to show the process
+    Error(String),
+    /// Aborted by a cancel request before it completed
+    Cancelled,
+    /// Network result, as the serialized result JSON
+    Response(Vec<u8>),
+}
+
+/// A finished async operation, ready to be delivered
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AsyncCompletion {
+    pub channel: AsyncChannel,
+    /// ID returned when the operation was submitted
+    pub request_id: u32,
+    /// Process that submitted it
+    pub pid: u64,
+    pub outcome: AsyncOutcome,
+}
+
+/// An async operation submitted but not yet completed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingAsyncOp {
+    pub channel: AsyncChannel,
+    pub request_id: u32,
+    /// Process waiting for it
+    pub pid: u64,
+    /// `HAL::now_nanos` when it was submitted
+    pub submitted_at: u64,
+}
+
+impl PendingAsyncOp {
+    /// How long the operation has been outstanding at `now` (nanoseconds).
+    pub fn age_nanos(&self, now: u64) -> u64 {
+        now.saturating_sub(self.submitted_at)
+    }
+}
+
+/// Encode `keys` as a JSON array of strings, the form `ListOk` carries.
+///
+/// For backends that produce key lists natively rather than as JSON.
+pub fn encode_key_list(keys: &[String]) -> Vec<u8> {
+    let mut json = Vec::with_capacity(2 + keys.iter().map(|k| k.len() + 3).sum::<usize>());
+    json.push(b'[');
+    for (i, key) in keys.iter().enumerate() {
+        if i > 0 {
+            json.push(b',');
+        }
+        json.push(b'"');
+        for c in key.chars() {
+            match c {
+                '"' => json.extend_from_slice(b"\\\""),
+                '\\' => json.extend_from_slice(b"\\\\"),
+                c if (c as u32) < 0x20 => {
+                    json.extend_from_slice(alloc::format!("\\u{:04x}", c as u32).as_bytes())
+                }
+                c => {
+                    let mut buf = [0u8; 4];
+                    json.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+            }
+        }
+        json.push(b'"');
+    }
+    json.push(b']');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn test_op_channels() {
+        assert_eq!(
+            AsyncOp::StorageRead { key: "/a" }.channel(),
+            AsyncChannel::Storage
+        );
+        assert_eq!(
+            AsyncOp::StorageBatchWrite { items: &[] }.channel(),
+            AsyncChannel::Storage
+        );
+        assert_eq!(
+            AsyncOp::KeystoreList { prefix: "/keys" }.channel(),
+            AsyncChannel::Keystore
+        );
+        assert_eq!(
+            AsyncOp::NetworkFetch { request: b"{}" }.channel(),
+            AsyncChannel::Network
+        );
+    }
+
+    #[test]
+    fn test_pending_op_age() {
+        let op = PendingAsyncOp {
+            channel: AsyncChannel::Network,
+            request_id: 4,
+            pid: 9,
+            submitted_at: 1_000,
+        };
+        assert_eq!(op.age_nanos(5_000), 4_000);
+        // A clock reading from before submission counts as no age
+        assert_eq!(op.age_nanos(500), 0);
+    }
+
+    #[test]
+    fn test_encode_key_list() {
+        assert_eq!(encode_key_list(&[]), b"[]");
+        let keys = vec![
+            "content:/home/a".to_string(),
+            "inode:/say \"hi\"".to_string(),
+            "back\\slash\n".to_string(),
+            "caf\u{e9}".to_string(),
+        ];
+        assert_eq!(
+            core::str::from_utf8(&encode_key_list(&keys)).unwrap(),
+            "[\"content:/home/a\",\"inode:/say \\\"hi\\\"\",\"back\\\\slash\\u000a\",\"caf\u{e9}\"]"
+        );
+    }
+}
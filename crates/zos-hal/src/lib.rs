@@ -23,6 +23,10 @@ extern crate alloc;
 #[cfg(feature = "x86_64")]
 pub mod x86_64;
 
+pub mod async_io;
+
+pub use async_io::{AsyncChannel, AsyncCompletion, AsyncOp, AsyncOutcome, PendingAsyncOp};
+
 use alloc::vec::Vec;
 
 /// Callback type for process message notifications
@@ -36,6 +40,7 @@ pub type MessageCallback<P> = fn(&P, &[u8]);
 /// - Time measurement
 /// - Entropy (random numbers)
 /// - Debug output
+/// - Async I/O (storage, keystore, network) submission and completion
 ///
 /// # Associated Types
 ///
@@ -116,6 +121,26 @@ pub trait HAL: Send + Sync + 'static {
     /// On WASM: Returns the linear memory size (pages * 64KB)
     fn get_process_memory_size(&self, handle: &Self::ProcessHandle) -> Result<usize, HalError>;
 
+    /// Set the scheduling priority class of a process
+    ///
+    /// Classes are `zos_ipc::priority` values; lower runs first. Platforms
+    /// that schedule processes themselves run ready processes in class
+    /// order.
+    fn set_process_priority(&self, _pid: u64, _priority: u8) {
+        // Default: no-op, processes are scheduled by the host (e.g. browser workers)
+    }
+
+    /// Read `len` bytes of a process's memory at `addr`, for a debugger
+    ///
+    /// # Returns
+    /// * `Ok(bytes)` - The bytes read
+    /// * `Err(HalError::ProcessNotFound)` - Process doesn't exist
+    /// * `Err(HalError::InvalidArgument)` - The range is outside its memory
+    /// * `Err(HalError::NotSupported)` - The platform cannot read it
+    fn read_process_memory(&self, _pid: u64, _addr: u32, _len: u32) -> Result<Vec<u8>, HalError> {
+        Err(HalError::NotSupported)
+    }
+
     // === Memory ===
 
     /// Allocate memory (within current context)
@@ -184,13 +209,78 @@ pub trait HAL: Send + Sync + 'static {
         // Default: no-op, use polling
     }
 
+    // === Async I/O ===
+    // The kernel submits every storage, keystore and network operation through
+    // submit_async and collects results with poll_async_completions, whatever
+    // the platform does underneath.
+
+    /// Submit an async operation for `pid` (returns immediately)
+    ///
+    /// The default forwards to the per-operation methods below, so a
+    /// platform only implements the operations it supports.
+    ///
+    /// # Returns
+    /// * `Ok(request_id)` - ID the completion will carry
+    /// * `Err(HalError)` - Failed to start operation
+    fn submit_async(&self, pid: u64, op: AsyncOp<'_>) -> Result<u32, HalError> {
+        match op {
+            AsyncOp::StorageRead { key } => self.storage_read_async(pid, key),
+            AsyncOp::StorageWrite { key, value } => self.storage_write_async(pid, key, value),
+            AsyncOp::StorageDelete { key } => self.storage_delete_async(pid, key),
+            AsyncOp::StorageList { prefix } => self.storage_list_async(pid, prefix),
+            AsyncOp::StorageExists { key } => self.storage_exists_async(pid, key),
+            AsyncOp::StorageBatchWrite { items } => self.storage_batch_write_async(pid, items),
+            AsyncOp::KeystoreRead { key } => self.keystore_read_async(pid, key),
+            AsyncOp::KeystoreWrite { key, value } => self.keystore_write_async(pid, key, value),
+            AsyncOp::KeystoreDelete { key } => self.keystore_delete_async(pid, key),
+            AsyncOp::KeystoreList { prefix } => self.keystore_list_async(pid, prefix),
+            AsyncOp::KeystoreExists { key } => self.keystore_exists_async(pid, key),
+            AsyncOp::NetworkFetch { request } => self.network_fetch_async(pid, request),
+        }
+    }
+
+    /// Take the async operations that finished since the last call
+    ///
+    /// Each completion's request is no longer pending: its PID can't be
+    /// looked up or taken any more. Completions come back in the order the
+    /// platform finished them.
+    fn poll_async_completions(&self) -> Vec<AsyncCompletion> {
+        Vec::new()
+    }
+
+    /// List the async operations still waiting for a completion
+    ///
+    /// Platforms that finish every operation before `submit_async` returns
+    /// have nothing to list.
+    fn pending_async_ops(&self) -> Vec<PendingAsyncOp> {
+        Vec::new()
+    }
+
+    /// Complete a pending async operation with `AsyncOutcome::Error(reason)`
+    ///
+    /// For operations the platform has stopped answering. The request stops
+    /// being pending straight away; if the platform reports it later, that
+    /// result is dropped as an orphan.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The error completion is queued for the next poll
+    /// * `Err(HalError::NotFound)` - No such request is pending
+    fn fail_async(
+        &self,
+        _channel: AsyncChannel,
+        _request_id: u32,
+        _reason: &str,
+    ) -> Result<(), HalError> {
+        Err(HalError::NotSupported)
+    }
+
     // === Async Platform Storage ===
     // These methods start async storage operations and return immediately with a request_id.
-    // Results are delivered via push callbacks (see notify_storage_* methods).
+    // Results are reported by poll_async_completions.
 
     /// Start async read from platform storage (returns immediately)
     ///
-    /// The result will be delivered via notify_storage_read_complete callback.
+    /// Completes with `ReadOk` or `NotFound`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation
@@ -205,7 +295,7 @@ pub trait HAL: Send + Sync + 'static {
 
     /// Start async write to platform storage (returns immediately)
     ///
-    /// The result will be delivered via notify_storage_write_complete callback.
+    /// Completes with `WriteOk`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation
@@ -226,7 +316,7 @@ pub trait HAL: Send + Sync + 'static {
 
     /// Start async delete from platform storage (returns immediately)
     ///
-    /// The result will be delivered via notify_storage_write_complete callback.
+    /// Completes with `WriteOk`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation
@@ -241,7 +331,7 @@ pub trait HAL: Send + Sync + 'static {
 
     /// Start async list keys with prefix (returns immediately)
     ///
-    /// The result will be delivered via notify_storage_list_complete callback.
+    /// Completes with `ListOk`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation
@@ -256,7 +346,7 @@ pub trait HAL: Send + Sync + 'static {
 
     /// Start async exists check (returns immediately)
     ///
-    /// The result will be delivered via notify_storage_exists_complete callback.
+    /// Completes with `ExistsOk`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation
@@ -275,7 +365,7 @@ pub trait HAL: Send + Sync + 'static {
     /// significantly reducing round-trip latency for operations like mkdir
     /// with create_parents=true.
     ///
-    /// The result will be delivered via notify_storage_write_complete callback.
+    /// Completes with `WriteOk`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation
@@ -292,6 +382,23 @@ pub trait HAL: Send + Sync + 'static {
         Err(HalError::NotSupported)
     }
 
+    /// Cancel a pending storage request
+    ///
+    /// The platform aborts the underlying transaction where it can, and the
+    /// request completes with `Cancelled`. A request whose result
+    /// was already produced completes normally instead.
+    ///
+    /// # Arguments
+    /// * `pid` - Process ID that started the request
+    /// * `request_id` - The request to cancel
+    ///
+    /// # Returns
+    /// * `Ok(())` - Cancellation requested
+    /// * `Err(HalError)` - Unknown request, or it belongs to another process
+    fn storage_cancel(&self, _pid: u64, _request_id: StorageRequestId) -> Result<(), HalError> {
+        Err(HalError::NotSupported)
+    }
+
     /// Get the PID associated with a pending storage request
     ///
     /// # Arguments
@@ -319,10 +426,11 @@ pub trait HAL: Send + Sync + 'static {
     // === Async Keystore (KeyService Only) ===
     // These methods provide access to the dedicated keystore (zos-keystore IndexedDB).
     // Only KeyService should use these syscalls - other processes use KeyService IPC.
+    // Results are reported by poll_async_completions.
 
     /// Start async read from keystore (returns immediately)
     ///
-    /// The result will be delivered via notify_keystore_read_complete callback.
+    /// Completes with `ReadOk` or `NotFound`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation (must be KeyService)
@@ -337,7 +445,7 @@ pub trait HAL: Send + Sync + 'static {
 
     /// Start async write to keystore (returns immediately)
     ///
-    /// The result will be delivered via notify_keystore_write_complete callback.
+    /// Completes with `WriteOk`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation (must be KeyService)
@@ -358,7 +466,7 @@ pub trait HAL: Send + Sync + 'static {
 
     /// Start async delete from keystore (returns immediately)
     ///
-    /// The result will be delivered via notify_keystore_write_complete callback.
+    /// Completes with `WriteOk`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation (must be KeyService)
@@ -373,7 +481,7 @@ pub trait HAL: Send + Sync + 'static {
 
     /// Start async list keys with prefix (returns immediately)
     ///
-    /// The result will be delivered via notify_keystore_list_complete callback.
+    /// Completes with `ListOk`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation (must be KeyService)
@@ -388,7 +496,7 @@ pub trait HAL: Send + Sync + 'static {
 
     /// Start async exists check on keystore (returns immediately)
     ///
-    /// The result will be delivered via notify_keystore_exists_complete callback.
+    /// Completes with `ExistsOk`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation (must be KeyService)
@@ -427,11 +535,11 @@ pub trait HAL: Send + Sync + 'static {
 
     // === Async Network Operations ===
     // These methods start async network (HTTP) operations and return immediately with a request_id.
-    // Results are delivered via push callbacks (see onNetworkResult in JS HAL).
+    // Results are reported by poll_async_completions.
 
     /// Start async HTTP fetch operation (returns immediately)
     ///
-    /// The result will be delivered via MSG_NET_RESULT IPC callback.
+    /// Completes with `Response`.
     ///
     /// # Arguments
     /// * `pid` - Process ID requesting the operation
@@ -48,6 +48,12 @@ mod ioapic_reg {
     pub const REDTBL_BASE: u8 = 0x10;
 }
 
+/// Interrupt command register fields
+mod icr {
+    pub const DELIVERY_NMI: u32 = 0b100 << 8; // Delivery mode NMI (bits 8-10)
+    pub const LEVEL_ASSERT: u32 = 1 << 14;    // Level: assert (bit 14)
+}
+
 /// Timer interrupt vector number (must match InterruptIndex::Timer)
 pub const TIMER_VECTOR: u8 = 32;
 
@@ -260,6 +266,17 @@ pub fn enable_timer() {
     }
 }
 
+/// Send an NMI to this CPU.
+///
+/// Addressed by APIC ID, since the self shorthand only allows fixed
+/// delivery.
+pub fn send_self_nmi() {
+    unsafe {
+        write_lapic(lapic_reg::ICR_HIGH, lapic_id() << 24);
+        write_lapic(lapic_reg::ICR_LOW, icr::DELIVERY_NMI | icr::LEVEL_ASSERT);
+    }
+}
+
 /// Get LAPIC ID
 pub fn lapic_id() -> u32 {
     unsafe { read_lapic(lapic_reg::ID) >> 24 }
@@ -0,0 +1,49 @@
+//! Deliberate fatal faults for the QEMU fault tests
+//!
+//! A kernel built with `ZOS_FAULT_TEST=<class>` raises that fault right
+//! after HAL init. The panic screen is armed for it first, so reaching the
+//! handler exits QEMU with [`FAULT_TEST_PASSED`]. A broken handler or IST
+//! setup shows up as a reset (triple fault, which `-no-reboot` turns into
+//! a plain exit) or a hang instead. `make qemu-fault-tests` boots once per
+//! class and checks the exit code.
+
+use crate::serial_println;
+
+use super::panic_screen::{self, FaultClass};
+pub use super::panic_screen::{FAULT_TEST_FAILED, FAULT_TEST_PASSED};
+
+/// A non-canonical address, unusable as a stack
+const NON_CANONICAL: u64 = 0x8000_0000_0000_0000;
+
+/// Raise `class` and let its handler end the test.
+pub fn run(class: FaultClass) -> ! {
+    serial_println!("Fault test: raising {}", class.name());
+    panic_screen::expect(class);
+
+    match class {
+        // Pushing onto a non-canonical stack raises #SS, and delivering
+        // the #SS on the same stack raises another, which escalates to a
+        // double fault. Only the IST stack keeps that from being a triple
+        // fault.
+        FaultClass::DoubleFault => unsafe {
+            core::arch::asm!(
+                "mov rsp, {}",
+                "push rax",
+                in(reg) NON_CANONICAL,
+                options(noreturn)
+            )
+        },
+        FaultClass::Nmi => {
+            // Delivered even with interrupts disabled
+            if super::apic::is_initialized() {
+                super::apic::send_self_nmi();
+            } else {
+                unsafe { core::arch::asm!("int 2") };
+            }
+        }
+        // Real machine checks can't be raised from software; INT 18 runs
+        // the handler on its IST stack, with empty error banks
+        FaultClass::MachineCheck => unsafe { core::arch::asm!("int 18") },
+    }
+    super::halt_loop()
+}
@@ -9,6 +9,9 @@ use x86_64::structures::gdt::{Descriptor, GlobalDescriptorTable, SegmentSelector
 use x86_64::structures::tss::TaskStateSegment;
 use x86_64::VirtAddr;
 
+use super::vmm::stack::{self, StackRange};
+use super::vmm::PAGE_SIZE;
+
 /// Size of the interrupt stack in bytes (16KB)
 pub const INTERRUPT_STACK_SIZE: usize = 4096 * 4;
 
@@ -18,14 +21,58 @@ pub const DOUBLE_FAULT_IST_INDEX: u16 = 0;
 /// Stack index for page fault handler (uses IST entry 1)
 pub const PAGE_FAULT_IST_INDEX: u16 = 1;
 
+/// Stack index for NMI handler (uses IST entry 2)
+///
+/// An NMI can arrive at any instruction, including while the current stack
+/// is unusable, so it never runs on the interrupted stack.
+pub const NMI_IST_INDEX: u16 = 2;
+
+/// Stack index for machine check handler (uses IST entry 3)
+pub const MACHINE_CHECK_IST_INDEX: u16 = 3;
+
 /// The Task State Segment
 static mut TSS: TaskStateSegment = TaskStateSegment::new();
 
+/// An interrupt stack with the page below it reserved as a guard page
+///
+/// The guard page is unmapped by `guard_interrupt_stacks` once the VMM is up.
+#[repr(C, align(4096))]
+struct GuardedStack {
+    #[allow(dead_code)] // Never accessed; only reserves the page
+    guard: [u8; PAGE_SIZE],
+    stack: [u8; INTERRUPT_STACK_SIZE],
+}
+
+impl GuardedStack {
+    const fn new() -> Self {
+        Self {
+            guard: [0; PAGE_SIZE],
+            stack: [0; INTERRUPT_STACK_SIZE],
+        }
+    }
+
+    /// Address one past the top of the stack
+    fn top(&self) -> VirtAddr {
+        VirtAddr::from_ptr(self.stack.as_ptr()) + INTERRUPT_STACK_SIZE as u64
+    }
+
+    fn range(&self, name: &'static str) -> StackRange {
+        let bottom = self.stack.as_ptr() as u64;
+        StackRange::fixed(name, bottom, bottom + INTERRUPT_STACK_SIZE as u64)
+    }
+}
+
 /// Interrupt stack storage for double fault
-static mut DOUBLE_FAULT_STACK: [u8; INTERRUPT_STACK_SIZE] = [0; INTERRUPT_STACK_SIZE];
+static mut DOUBLE_FAULT_STACK: GuardedStack = GuardedStack::new();
 
 /// Interrupt stack storage for page fault
-static mut PAGE_FAULT_STACK: [u8; INTERRUPT_STACK_SIZE] = [0; INTERRUPT_STACK_SIZE];
+static mut PAGE_FAULT_STACK: GuardedStack = GuardedStack::new();
+
+/// Interrupt stack storage for NMI
+static mut NMI_STACK: GuardedStack = GuardedStack::new();
+
+/// Interrupt stack storage for machine check
+static mut MACHINE_CHECK_STACK: GuardedStack = GuardedStack::new();
 
 /// The Global Descriptor Table
 static mut GDT: Option<(GlobalDescriptorTable, Selectors)> = None;
@@ -50,17 +97,22 @@ pub unsafe fn init() {
     // Set up the TSS with interrupt stacks
     // Use raw pointers to avoid creating references to mutable statics
     let double_fault_stack_ptr = &raw const DOUBLE_FAULT_STACK;
-    let double_fault_stack_end = 
-        VirtAddr::from_ptr((*double_fault_stack_ptr).as_ptr()) + INTERRUPT_STACK_SIZE as u64;
+    let double_fault_stack_end = (*double_fault_stack_ptr).top();
     
     let tss_ptr = &raw mut TSS;
     (*tss_ptr).interrupt_stack_table[DOUBLE_FAULT_IST_INDEX as usize] = double_fault_stack_end;
 
     let page_fault_stack_ptr = &raw const PAGE_FAULT_STACK;
-    let page_fault_stack_end = 
-        VirtAddr::from_ptr((*page_fault_stack_ptr).as_ptr()) + PAGE_FAULT_STACK_SIZE as u64;
+    let page_fault_stack_end = (*page_fault_stack_ptr).top();
     (*tss_ptr).interrupt_stack_table[PAGE_FAULT_IST_INDEX as usize] = page_fault_stack_end;
 
+    let nmi_stack_ptr = &raw const NMI_STACK;
+    (*tss_ptr).interrupt_stack_table[NMI_IST_INDEX as usize] = (*nmi_stack_ptr).top();
+
+    let machine_check_stack_ptr = &raw const MACHINE_CHECK_STACK;
+    (*tss_ptr).interrupt_stack_table[MACHINE_CHECK_IST_INDEX as usize] =
+        (*machine_check_stack_ptr).top();
+
     // Build the GDT
     let mut gdt = GlobalDescriptorTable::new();
     let code_selector = gdt.append(Descriptor::kernel_code_segment());
@@ -93,8 +145,23 @@ pub unsafe fn init() {
     load_tss(selectors.tss_selector);
 }
 
-/// Stack size constant for page fault (same as interrupt stack)
-const PAGE_FAULT_STACK_SIZE: usize = INTERRUPT_STACK_SIZE;
+/// Unmap the guard pages below the interrupt stacks
+///
+/// # Safety
+/// Must be called once, after `init` and after the VMM is initialized.
+pub unsafe fn guard_interrupt_stacks() {
+    let stacks = [
+        ("double fault", &raw const DOUBLE_FAULT_STACK),
+        ("page fault", &raw const PAGE_FAULT_STACK),
+        ("NMI", &raw const NMI_STACK),
+        ("machine check", &raw const MACHINE_CHECK_STACK),
+    ];
+    for (name, ptr) in stacks {
+        if let Err(e) = stack::guard_kernel_stack((*ptr).range(name)) {
+            crate::serial_println!("GDT: Failed to guard {} stack: {}", name, e);
+        }
+    }
+}
 
 /// Get the kernel code selector
 pub fn kernel_code_selector() -> SegmentSelector {
@@ -12,7 +12,11 @@
 
 use crate::serial_println;
 use super::apic;
-use super::gdt::{DOUBLE_FAULT_IST_INDEX, PAGE_FAULT_IST_INDEX};
+use super::gdt::{
+    DOUBLE_FAULT_IST_INDEX, MACHINE_CHECK_IST_INDEX, NMI_IST_INDEX, PAGE_FAULT_IST_INDEX,
+};
+use super::mce;
+use super::panic_screen::{self, FaultClass};
 use spin::Lazy;
 use x86_64::structures::idt::{InterruptDescriptorTable, InterruptStackFrame, PageFaultErrorCode};
 
@@ -38,18 +42,25 @@ static IDT: Lazy<InterruptDescriptorTable> = Lazy::new(|| {
     // CPU exceptions
     idt.divide_error.set_handler_fn(divide_error_handler);
     idt.debug.set_handler_fn(debug_handler);
-    idt.non_maskable_interrupt.set_handler_fn(nmi_handler);
     idt.breakpoint.set_handler_fn(breakpoint_handler);
     idt.overflow.set_handler_fn(overflow_handler);
     idt.bound_range_exceeded.set_handler_fn(bound_range_handler);
     idt.invalid_opcode.set_handler_fn(invalid_opcode_handler);
     idt.device_not_available.set_handler_fn(device_not_available_handler);
 
-    // Double fault uses a separate stack to handle stack overflow
+    // Double fault, NMI and machine check each get their own stack: they
+    // must work even when the interrupted stack is gone, and a fault in
+    // their delivery would otherwise be a triple fault
     unsafe {
         idt.double_fault
             .set_handler_fn(double_fault_handler)
             .set_stack_index(DOUBLE_FAULT_IST_INDEX);
+        idt.non_maskable_interrupt
+            .set_handler_fn(nmi_handler)
+            .set_stack_index(NMI_IST_INDEX);
+        idt.machine_check
+            .set_handler_fn(machine_check_handler)
+            .set_stack_index(MACHINE_CHECK_IST_INDEX);
     }
 
     idt.invalid_tss.set_handler_fn(invalid_tss_handler);
@@ -66,7 +77,6 @@ static IDT: Lazy<InterruptDescriptorTable> = Lazy::new(|| {
 
     idt.x87_floating_point.set_handler_fn(x87_floating_point_handler);
     idt.alignment_check.set_handler_fn(alignment_check_handler);
-    idt.machine_check.set_handler_fn(machine_check_handler);
     idt.simd_floating_point.set_handler_fn(simd_floating_point_handler);
 
     // Hardware interrupts (IRQs)
@@ -82,6 +92,8 @@ static IDT: Lazy<InterruptDescriptorTable> = Lazy::new(|| {
 /// Initialize the IDT
 pub fn init() {
     IDT.load();
+    // SAFETY: the machine check handler is installed
+    unsafe { mce::init() };
 }
 
 // === Exception Handlers ===
@@ -97,8 +109,33 @@ extern "x86-interrupt" fn debug_handler(stack_frame: InterruptStackFrame) {
     serial_println!("EXCEPTION: DEBUG\n{:#?}", stack_frame);
 }
 
+/// Nothing in the kernel raises NMIs (no watchdog, single CPU), so one
+/// means a hardware error and is fatal.
 extern "x86-interrupt" fn nmi_handler(stack_frame: InterruptStackFrame) {
-    serial_println!("EXCEPTION: NON-MASKABLE INTERRUPT\n{:#?}", stack_frame);
+    // SAFETY: reading system control port B has no side effects
+    let port_b: u8 = unsafe { x86_64::instructions::port::Port::new(0x61).read() };
+    panic_screen::fatal_exception(
+        "EXCEPTION: NON-MASKABLE INTERRUPT",
+        &stack_frame,
+        Some(FaultClass::Nmi),
+        |screen| {
+            screen.line(format_args!(
+                "Reason: {} (port 0x61 = {:#04x})",
+                nmi_reason(port_b),
+                port_b
+            ))
+        },
+    )
+}
+
+/// Decode the NMI source bits of system control port B.
+fn nmi_reason(port_b: u8) -> &'static str {
+    match (port_b & 0x80 != 0, port_b & 0x40 != 0) {
+        (true, true) => "memory parity error and I/O channel check",
+        (true, false) => "memory parity error / SERR#",
+        (false, true) => "I/O channel check",
+        (false, false) => "not from the system board",
+    }
 }
 
 extern "x86-interrupt" fn breakpoint_handler(stack_frame: InterruptStackFrame) {
@@ -126,12 +163,26 @@ extern "x86-interrupt" fn device_not_available_handler(stack_frame: InterruptSta
 
 extern "x86-interrupt" fn double_fault_handler(
     stack_frame: InterruptStackFrame,
-    _error_code: u64,
+    error_code: u64,
 ) -> ! {
-    serial_println!("EXCEPTION: DOUBLE FAULT\n{:#?}", stack_frame);
-    loop {
-        x86_64::instructions::hlt();
-    }
+    use super::vmm::stack::{self, StackFault};
+
+    let rsp = stack_frame.stack_pointer.as_u64();
+    panic_screen::fatal_exception(
+        "EXCEPTION: DOUBLE FAULT",
+        &stack_frame,
+        Some(FaultClass::DoubleFault),
+        |screen| {
+            screen.line(format_args!("Error Code: {}", error_code));
+            // A fault whose delivery overflows the stack escalates to here
+            if let StackFault::Overflow { stack, size, .. } = stack::classify_kernel_fault(rsp, rsp) {
+                screen.line(format_args!(
+                    "Probable cause: {} stack overflow ({} bytes)",
+                    stack, size
+                ));
+            }
+        },
+    )
 }
 
 extern "x86-interrupt" fn invalid_tss_handler(stack_frame: InterruptStackFrame, error_code: u64) {
@@ -191,13 +242,55 @@ extern "x86-interrupt" fn page_fault_handler(
     stack_frame: InterruptStackFrame,
     error_code: PageFaultErrorCode,
 ) {
+    use super::vmm::stack::{self, StackFault};
     use x86_64::registers::control::Cr2;
-    serial_println!(
-        "EXCEPTION: PAGE FAULT\nAccessed Address: {:?}\nError Code: {:?}\n{:#?}",
-        Cr2::read(),
-        error_code,
-        stack_frame
-    );
+
+    let addr = Cr2::read_raw();
+    let rsp = stack_frame.stack_pointer.as_u64();
+
+    // Services run in the WASM interpreter in ring 0, so every fault here
+    // is a kernel fault; user stacks grow via AddressSpace::handle_stack_fault
+    match stack::classify_kernel_fault(addr, rsp) {
+        StackFault::Overflow {
+            stack,
+            size,
+            past_limit,
+        } => serial_println!(
+            "EXCEPTION: STACK OVERFLOW\nStack: {} ({} bytes)\nAccessed Address: {:#x} ({} bytes past the end)\nStack Pointer: {:#x}\nInstruction: {:?}",
+            stack,
+            size,
+            addr,
+            past_limit,
+            rsp,
+            stack_frame.instruction_pointer
+        ),
+        StackFault::ProbableOverflow => serial_println!(
+            "EXCEPTION: PAGE FAULT (probable stack overflow: just below stack pointer)\nAccessed Address: {:#x}\nStack Pointer: {:#x}\nError Code: {:?}\n{:#?}",
+            addr,
+            rsp,
+            error_code,
+            stack_frame
+        ),
+        StackFault::Grow { .. } | StackFault::NotStack => {
+            let violation = error_code.contains(PageFaultErrorCode::PROTECTION_VIOLATION);
+            let kind = if violation && error_code.contains(PageFaultErrorCode::INSTRUCTION_FETCH) {
+                " (W^X: instruction fetch from a no-execute page)"
+            } else if violation && error_code.contains(PageFaultErrorCode::CAUSED_BY_WRITE) {
+                " (write to a read-only page)"
+            } else {
+                ""
+            };
+            serial_println!(
+                "EXCEPTION: PAGE FAULT{}\nAccessed Address: {:#x}\nError Code: {:?}\n{:#?}",
+                kind,
+                addr,
+                error_code,
+                stack_frame
+            )
+        }
+    }
+    // Code addresses are randomized; this maps them back to the ELF
+    serial_println!("Kernel image offset: {:#x}", super::vmm::kernel_image_offset());
     loop {
         x86_64::instructions::hlt();
     }
@@ -219,10 +312,12 @@ extern "x86-interrupt" fn alignment_check_handler(
 }
 
 extern "x86-interrupt" fn machine_check_handler(stack_frame: InterruptStackFrame) -> ! {
-    serial_println!("EXCEPTION: MACHINE CHECK\n{:#?}", stack_frame);
-    loop {
-        x86_64::instructions::hlt();
-    }
+    panic_screen::fatal_exception(
+        "EXCEPTION: MACHINE CHECK",
+        &stack_frame,
+        Some(FaultClass::MachineCheck),
+        mce::report,
+    )
 }
 
 extern "x86-interrupt" fn simd_floating_point_handler(stack_frame: InterruptStackFrame) {
@@ -0,0 +1,209 @@
+//! Machine check architecture (MCA) support
+//!
+//! With CR4.MCE clear, a machine check shuts the processor down, which
+//! looks exactly like a triple fault. [`init`] sets it so machine checks
+//! reach the IDT, and [`report`] prints the error banks from the handler.
+
+use x86_64::registers::control::{Cr4, Cr4Flags};
+use x86_64::registers::model_specific::Msr;
+
+use super::panic_screen::PanicScreen;
+
+/// IA32_MCG_CAP: bank count in bits 0-7
+const IA32_MCG_CAP: u32 = 0x179;
+
+/// IA32_MCG_STATUS: global machine check state
+const IA32_MCG_STATUS: u32 = 0x17A;
+
+/// IA32_MC0_STATUS; bank `i` has STATUS, ADDR and MISC at `+ 4 * i`
+const IA32_MC0_STATUS: u32 = 0x401;
+
+/// Most banks read by [`report`]
+pub const MAX_BANKS: u32 = 32;
+
+/// CPUID.1:EDX bits
+const CPUID_MCE: u32 = 1 << 7;
+const CPUID_MCA: u32 = 1 << 14;
+
+/// Decoded IA32_MCi_STATUS
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BankStatus(pub u64);
+
+impl BankStatus {
+    /// The bank holds a logged error
+    pub const fn valid(self) -> bool {
+        self.0 & (1 << 63) != 0
+    }
+
+    /// An earlier error was lost
+    pub const fn overflow(self) -> bool {
+        self.0 & (1 << 62) != 0
+    }
+
+    /// The error was not corrected by hardware
+    pub const fn uncorrected(self) -> bool {
+        self.0 & (1 << 61) != 0
+    }
+
+    /// IA32_MCi_MISC holds extra information
+    pub const fn misc_valid(self) -> bool {
+        self.0 & (1 << 59) != 0
+    }
+
+    /// IA32_MCi_ADDR holds the error address
+    pub const fn addr_valid(self) -> bool {
+        self.0 & (1 << 58) != 0
+    }
+
+    /// Processor context is corrupt; execution can't safely continue
+    pub const fn context_corrupt(self) -> bool {
+        self.0 & (1 << 57) != 0
+    }
+
+    /// Architectural MCA error code
+    pub const fn mca_code(self) -> u16 {
+        self.0 as u16
+    }
+}
+
+/// Decoded IA32_MCG_STATUS
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlobalStatus(pub u64);
+
+impl GlobalStatus {
+    /// Execution can restart at the interrupted instruction
+    pub const fn restart_ip_valid(self) -> bool {
+        self.0 & 1 != 0
+    }
+
+    /// The interrupted instruction is related to the error
+    pub const fn error_ip_valid(self) -> bool {
+        self.0 & 2 != 0
+    }
+}
+
+/// Whether the CPU supports machine checks and the MCA banks.
+fn supported() -> (bool, bool) {
+    // CPUID leaf 1; rbx is saved by hand since LLVM reserves it
+    let edx: u32;
+    unsafe {
+        core::arch::asm!(
+            "push rbx",
+            "mov eax, 1",
+            "cpuid",
+            "pop rbx",
+            out("edx") edx,
+            out("eax") _,
+            out("ecx") _,
+            options(preserves_flags),
+        );
+    }
+    (edx & CPUID_MCE != 0, edx & CPUID_MCA != 0)
+}
+
+/// Route machine checks to the IDT instead of shutting down.
+///
+/// # Safety
+/// The IDT must have a machine check handler installed.
+pub unsafe fn init() {
+    if supported().0 {
+        Cr4::update(|flags| flags.insert(Cr4Flags::MACHINE_CHECK_EXCEPTION));
+    }
+}
+
+/// Print the global status and every bank with a logged error.
+pub fn report(screen: &mut PanicScreen) {
+    if !supported().1 {
+        screen.line(format_args!("MCA: not supported, no error banks"));
+        return;
+    }
+    // SAFETY: the MCA MSRs exist when CPUID reports MCA, and the bank
+    // index stays below the count in IA32_MCG_CAP
+    unsafe {
+        let banks = (Msr::new(IA32_MCG_CAP).read() as u32 & 0xFF).min(MAX_BANKS);
+        let global = GlobalStatus(Msr::new(IA32_MCG_STATUS).read());
+        screen.line(format_args!(
+            "MCG_STATUS: {:#x} (restart IP {}, error IP {}), {} banks",
+            global.0,
+            if global.restart_ip_valid() {
+                "valid"
+            } else {
+                "invalid"
+            },
+            if global.error_ip_valid() {
+                "valid"
+            } else {
+                "invalid"
+            },
+            banks
+        ));
+
+        let mut logged = 0;
+        for bank in 0..banks {
+            let base = IA32_MC0_STATUS + 4 * bank;
+            let status = BankStatus(Msr::new(base).read());
+            if !status.valid() {
+                continue;
+            }
+            logged += 1;
+            screen.line(format_args!(
+                "MC{}: status {:#018x} code {:#06x}{}{}{}",
+                bank,
+                status.0,
+                status.mca_code(),
+                if status.uncorrected() {
+                    " uncorrected"
+                } else {
+                    " corrected"
+                },
+                if status.context_corrupt() {
+                    " context-corrupt"
+                } else {
+                    ""
+                },
+                if status.overflow() { " overflow" } else { "" }
+            ));
+            if status.addr_valid() {
+                screen.line(format_args!(
+                    "MC{}: addr {:#x}",
+                    bank,
+                    Msr::new(base + 1).read()
+                ));
+            }
+            if status.misc_valid() {
+                screen.line(format_args!(
+                    "MC{}: misc {:#x}",
+                    bank,
+                    Msr::new(base + 2).read()
+                ));
+            }
+        }
+        if logged == 0 {
+            screen.line(format_args!("MCA: no bank holds an error"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bank_status_decoding() {
+        let status = BankStatus((1 << 63) | (1 << 61) | (1 << 58) | 0x0150);
+        assert!(status.valid());
+        assert!(status.uncorrected());
+        assert!(status.addr_valid());
+        assert!(!status.misc_valid());
+        assert!(!status.context_corrupt());
+        assert_eq!(status.mca_code(), 0x0150);
+        assert!(!BankStatus(0x0150).valid());
+    }
+
+    #[test]
+    fn test_global_status_decoding() {
+        assert!(GlobalStatus(0b01).restart_ip_valid());
+        assert!(!GlobalStatus(0b01).error_ip_valid());
+        assert!(GlobalStatus(0b10).error_ip_valid());
+    }
+}
@@ -8,14 +8,18 @@
 //! - **Serial**: COM1 serial port driver for debug output
 //! - **GDT**: Global Descriptor Table with TSS for interrupt handling
 //! - **Interrupts**: Interrupt Descriptor Table for exception handling
+//! - **Panic screen**: Diagnostics for fatal faults and kernel panics
 //! - **VMM**: Virtual Memory Manager with 4-level page tables
 //! - **APIC**: Local APIC for timer and interrupt handling
 //! - **VirtIO**: VirtIO device drivers (block, network, etc.)
 //! - **WASM**: WASM runtime for executing service binaries
 
 pub mod apic;
+pub mod fault_test;
 pub mod gdt;
 pub mod interrupts;
+pub mod mce;
+pub mod panic_screen;
 pub mod pci;
 pub mod random;
 pub mod rtc;
@@ -69,7 +73,10 @@ use alloc::vec::Vec;
 use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use spin::Mutex;
 
-use crate::{HalError, NumericProcessHandle, StorageRequestId, HAL};
+use crate::{
+    AsyncChannel, AsyncCompletion, AsyncOutcome, HalError, NumericProcessHandle, StorageRequestId,
+    HAL,
+};
 
 // Re-export WASM runtime types
 pub use wasm::{WasmRuntime, PendingSyscall};
@@ -85,20 +92,6 @@ static GLOBAL_WASM_RUNTIME: spin::Once<WasmRuntime> = spin::Once::new();
 /// Maximum pending storage requests
 const MAX_PENDING_STORAGE_REQUESTS: usize = 1000;
 
-/// Storage request state
-#[derive(Clone, Debug)]
-#[allow(dead_code)] // Fields are stored for future retrieval but read operations not yet implemented
-enum StorageRequestState {
-    /// Read completed with result
-    ReadComplete(Option<Vec<u8>>),
-    /// Write/delete completed
-    WriteComplete(bool),
-    /// List completed with keys
-    ListComplete(Vec<String>),
-    /// Exists check completed
-    ExistsComplete(bool),
-}
-
 /// x86_64 Hardware Abstraction Layer implementation
 ///
 /// Provides platform-specific functionality for x86_64 targets:
@@ -117,8 +110,8 @@ pub struct X86_64Hal {
     messages: Mutex<Vec<(NumericProcessHandle, Vec<u8>)>>,
     /// Next storage request ID
     next_storage_request_id: AtomicU32,
-    /// Pending storage requests: request_id -> (pid, result)
-    storage_requests: Mutex<BTreeMap<StorageRequestId, (u64, StorageRequestState)>>,
+    /// Storage requests that have completed but not been polled yet
+    storage_completions: Mutex<Vec<AsyncCompletion>>,
     /// Storage initialized flag
     storage_initialized: Mutex<bool>,
     /// Pending IPC messages for processes: pid -> Vec<message_bytes>
@@ -133,7 +126,7 @@ impl X86_64Hal {
             next_pid: AtomicU64::new(1), // PID 0 reserved for kernel
             messages: Mutex::new(Vec::new()),
             next_storage_request_id: AtomicU32::new(1),
-            storage_requests: Mutex::new(BTreeMap::new()),
+            storage_completions: Mutex::new(Vec::new()),
             storage_initialized: Mutex::new(false),
             pending_ipc: Mutex::new(BTreeMap::new()),
         }
@@ -152,6 +145,31 @@ impl X86_64Hal {
     fn alloc_storage_request_id(&self) -> StorageRequestId {
         self.next_storage_request_id.fetch_add(1, Ordering::SeqCst)
     }
+
+    /// Run a storage operation now and queue its completion.
+    ///
+    /// Storage here is synchronous, so the completion is ready before the
+    /// request ID is even returned; the process still only sees it once the
+    /// kernel polls for completions.
+    fn run_storage_request(
+        &self,
+        pid: u64,
+        run: impl FnOnce() -> AsyncOutcome,
+    ) -> Result<StorageRequestId, HalError> {
+        let mut completions = self.storage_completions.lock();
+        if completions.len() >= MAX_PENDING_STORAGE_REQUESTS {
+            return Err(HalError::ResourceExhausted);
+        }
+
+        let request_id = self.alloc_storage_request_id();
+        completions.push(AsyncCompletion {
+            channel: AsyncChannel::Storage,
+            request_id,
+            pid,
+            outcome: run(),
+        });
+        Ok(request_id)
+    }
     
     /// Allocate a new process ID
     fn alloc_pid(&self) -> u64 {
@@ -211,6 +229,12 @@ impl X86_64Hal {
         // Initialize VMM with physical memory info
         vmm::init(physical_memory_offset, memory_regions);
 
+        // Guard pages need the VMM; record the boot stack for fault reports
+        gdt::guard_interrupt_stacks();
+        if let Err(e) = vmm::stack::register_boot_stack(vmm::stack::current_stack_pointer()) {
+            crate::serial_println!("VMM: Failed to register boot stack: {}", e);
+        }
+
         // Initialize APIC (timer will start after interrupts are enabled)
         apic::init();
 
@@ -341,6 +365,10 @@ impl HAL for X86_64Hal {
         self.wasm_runtime().memory_size(handle.id())
     }
 
+    fn set_process_priority(&self, pid: u64, priority: u8) {
+        self.wasm_runtime().set_priority(pid, priority)
+    }
+
     // === Memory ===
 
     fn allocate(&self, size: usize, align: usize) -> Result<*mut u8, HalError> {
@@ -426,80 +454,43 @@ impl HAL for X86_64Hal {
 
     // === Async Storage Operations ===
     // On x86_64, storage operations are synchronous internally but use the
-    // async API pattern for consistency with other platforms.
+    // async API pattern for consistency with other platforms: the completion
+    // is queued before the request ID is returned.
 
     fn storage_read_async(&self, pid: u64, key: &str) -> Result<StorageRequestId, HalError> {
-        let mut requests = self.storage_requests.lock();
-        if requests.len() >= MAX_PENDING_STORAGE_REQUESTS {
-            return Err(HalError::ResourceExhausted);
-        }
-
-        let request_id = self.alloc_storage_request_id();
-        
-        // Perform synchronous read
-        let result = match storage::read(key) {
-            Ok(data) => StorageRequestState::ReadComplete(data),
-            Err(_) => StorageRequestState::ReadComplete(None),
-        };
-        
-        requests.insert(request_id, (pid, result));
-        Ok(request_id)
+        self.run_storage_request(pid, || match storage::read(key) {
+            Ok(Some(data)) => AsyncOutcome::ReadOk(data),
+            Ok(None) => AsyncOutcome::NotFound,
+            Err(_) => AsyncOutcome::Error(String::from("Storage read failed")),
+        })
     }
 
     fn storage_write_async(&self, pid: u64, key: &str, value: &[u8]) -> Result<StorageRequestId, HalError> {
-        let mut requests = self.storage_requests.lock();
-        if requests.len() >= MAX_PENDING_STORAGE_REQUESTS {
-            return Err(HalError::ResourceExhausted);
-        }
-
-        let request_id = self.alloc_storage_request_id();
-        
-        // Perform synchronous write
-        let success = storage::write(key, value).is_ok();
-        requests.insert(request_id, (pid, StorageRequestState::WriteComplete(success)));
-        Ok(request_id)
+        self.run_storage_request(pid, || match storage::write(key, value) {
+            Ok(()) => AsyncOutcome::WriteOk,
+            Err(_) => AsyncOutcome::Error(String::from("Storage write failed")),
+        })
     }
 
     fn storage_delete_async(&self, pid: u64, key: &str) -> Result<StorageRequestId, HalError> {
-        let mut requests = self.storage_requests.lock();
-        if requests.len() >= MAX_PENDING_STORAGE_REQUESTS {
-            return Err(HalError::ResourceExhausted);
-        }
-
-        let request_id = self.alloc_storage_request_id();
-        
-        // Perform synchronous delete
-        let success = storage::delete(key).unwrap_or(false);
-        requests.insert(request_id, (pid, StorageRequestState::WriteComplete(success)));
-        Ok(request_id)
+        // Deleting a missing key succeeds, as it does in the browser
+        self.run_storage_request(pid, || match storage::delete(key) {
+            Ok(_) => AsyncOutcome::WriteOk,
+            Err(_) => AsyncOutcome::Error(String::from("Storage delete failed")),
+        })
     }
 
     fn storage_list_async(&self, pid: u64, prefix: &str) -> Result<StorageRequestId, HalError> {
-        let mut requests = self.storage_requests.lock();
-        if requests.len() >= MAX_PENDING_STORAGE_REQUESTS {
-            return Err(HalError::ResourceExhausted);
-        }
-
-        let request_id = self.alloc_storage_request_id();
-        
-        // Perform synchronous list
-        let keys = storage::list(prefix);
-        requests.insert(request_id, (pid, StorageRequestState::ListComplete(keys)));
-        Ok(request_id)
+        self.run_storage_request(pid, || {
+            AsyncOutcome::ListOk(crate::async_io::encode_key_list(&storage::list(prefix)))
+        })
     }
 
     fn storage_exists_async(&self, pid: u64, key: &str) -> Result<StorageRequestId, HalError> {
-        let mut requests = self.storage_requests.lock();
-        if requests.len() >= MAX_PENDING_STORAGE_REQUESTS {
-            return Err(HalError::ResourceExhausted);
-        }
-
-        let request_id = self.alloc_storage_request_id();
-        
-        // Perform synchronous exists check
-        let exists = storage::exists(key).unwrap_or(false);
-        requests.insert(request_id, (pid, StorageRequestState::ExistsComplete(exists)));
-        Ok(request_id)
+        self.run_storage_request(pid, || match storage::exists(key) {
+            Ok(exists) => AsyncOutcome::ExistsOk(exists),
+            Err(_) => AsyncOutcome::Error(String::from("Storage exists check failed")),
+        })
     }
 
     fn storage_batch_write_async(
@@ -507,32 +498,33 @@ impl HAL for X86_64Hal {
         pid: u64,
         items: &[(&str, &[u8])],
     ) -> Result<StorageRequestId, HalError> {
-        let mut requests = self.storage_requests.lock();
-        if requests.len() >= MAX_PENDING_STORAGE_REQUESTS {
-            return Err(HalError::ResourceExhausted);
-        }
-
-        let request_id = self.alloc_storage_request_id();
-
-        // Perform synchronous batch write (x86_64 doesn't have true batch, just iterate)
-        let mut success = true;
-        for (key, value) in items {
-            if storage::write(key, value).is_err() {
-                success = false;
-                break;
+        // x86_64 doesn't have true batch, just iterate
+        self.run_storage_request(pid, || {
+            for (key, value) in items {
+                if storage::write(key, value).is_err() {
+                    return AsyncOutcome::Error(String::from("Storage batch write failed"));
+                }
             }
-        }
-
-        requests.insert(request_id, (pid, StorageRequestState::WriteComplete(success)));
-        Ok(request_id)
+            AsyncOutcome::WriteOk
+        })
     }
 
     fn get_storage_request_pid(&self, request_id: StorageRequestId) -> Option<u64> {
-        self.storage_requests.lock().get(&request_id).map(|(pid, _)| *pid)
+        self.storage_completions
+            .lock()
+            .iter()
+            .find(|c| c.request_id == request_id)
+            .map(|c| c.pid)
     }
 
     fn take_storage_request_pid(&self, request_id: StorageRequestId) -> Option<u64> {
-        self.storage_requests.lock().remove(&request_id).map(|(pid, _)| pid)
+        let mut completions = self.storage_completions.lock();
+        let index = completions.iter().position(|c| c.request_id == request_id)?;
+        Some(completions.remove(index).pid)
+    }
+
+    fn poll_async_completions(&self) -> Vec<AsyncCompletion> {
+        core::mem::take(&mut *self.storage_completions.lock())
     }
 
     // === Bootstrap Storage (Supervisor Only) ===
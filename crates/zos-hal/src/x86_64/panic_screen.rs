@@ -0,0 +1,197 @@
+//! Panic screen: last-resort diagnostics for fatal faults and panics
+//!
+//! Fatal exception handlers and the kernel panic handler end here. The
+//! screen takes over the serial port (breaking any lock the interrupted
+//! code held), prints the fault and CPU state, and stops the CPU with
+//! interrupts off.
+//!
+//! Only one screen is ever drawn. A fault or NMI that arrives while it is
+//! being drawn just halts the CPU, so a broken handler can't cascade into
+//! a triple fault and reset the machine with the report half written.
+//!
+//! Under the QEMU fault tests ([`super::fault_test`]) the screen exits
+//! QEMU instead of halting, reporting whether the fault was the expected
+//! one.
+
+use core::fmt::{self, Write};
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use x86_64::registers::control::{Cr0, Cr2, Cr3, Cr4};
+use x86_64::structures::idt::InterruptStackFrame;
+
+use super::serial::{self, SerialWriter};
+
+/// QEMU exit code for a fault test whose fault reached the panic screen
+pub const FAULT_TEST_PASSED: u32 = 0x10;
+
+/// QEMU exit code for a fault test that stopped for any other reason
+pub const FAULT_TEST_FAILED: u32 = 0x11;
+
+const RULE: &str = "========================================";
+
+/// Fatal faults that run on a dedicated IST stack
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FaultClass {
+    DoubleFault = 1,
+    Nmi = 2,
+    MachineCheck = 3,
+}
+
+impl FaultClass {
+    /// Every class, in vector order
+    pub const ALL: [Self; 3] = [Self::DoubleFault, Self::Nmi, Self::MachineCheck];
+
+    /// Name used by the fault tests
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::DoubleFault => "double-fault",
+            Self::Nmi => "nmi",
+            Self::MachineCheck => "machine-check",
+        }
+    }
+
+    /// Look up a class by its test name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|class| class.name() == name)
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|class| *class as u8 == value)
+    }
+}
+
+/// Set once a screen has started drawing
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Fault the running fault test expects (0 outside fault tests)
+static EXPECTED: AtomicU8 = AtomicU8::new(0);
+
+/// Make the panic screen exit QEMU with a verdict on `class`.
+pub fn expect(class: FaultClass) {
+    EXPECTED.store(class as u8, Ordering::SeqCst);
+}
+
+/// A panic screen being drawn
+pub struct PanicScreen {
+    _private: (),
+}
+
+impl PanicScreen {
+    /// Disable interrupts, take over the serial port and print the header.
+    ///
+    /// Returns `None` if a screen is already being drawn; the caller
+    /// should halt without output.
+    pub fn begin(title: &str) -> Option<Self> {
+        x86_64::instructions::interrupts::disable();
+        if ACTIVE.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        // SAFETY: nothing interrupted by a fatal fault or panic resumes
+        unsafe { serial::force_unlock() };
+
+        let mut screen = Self { _private: () };
+        screen.line(format_args!(""));
+        screen.line(format_args!("{}", RULE));
+        screen.line(format_args!("  {}", title));
+        screen.line(format_args!("{}", RULE));
+        Some(screen)
+    }
+
+    /// Print one line.
+    pub fn line(&mut self, args: fmt::Arguments) {
+        let _ = SerialWriter.write_fmt(args);
+        let _ = SerialWriter.write_str("\n");
+    }
+
+    /// Print the interrupted context.
+    pub fn frame(&mut self, frame: &InterruptStackFrame) {
+        self.line(format_args!(
+            "RIP: {:#018x}  CS: {:#06x}",
+            frame.instruction_pointer.as_u64(),
+            frame.code_segment.0
+        ));
+        self.line(format_args!(
+            "RSP: {:#018x}  SS: {:#06x}",
+            frame.stack_pointer.as_u64(),
+            frame.stack_segment.0
+        ));
+        self.line(format_args!("RFLAGS: {:#x}", frame.cpu_flags.bits()));
+    }
+
+    /// Print the control registers.
+    pub fn control_registers(&mut self) {
+        let (pml4, _) = Cr3::read();
+        self.line(format_args!(
+            "CR0: {:#x}  CR2: {:#x}",
+            Cr0::read_raw(),
+            Cr2::read_raw()
+        ));
+        self.line(format_args!(
+            "CR3: {:#x}  CR4: {:#x}",
+            pml4.start_address().as_u64(),
+            Cr4::read_raw()
+        ));
+    }
+
+    /// Finish the screen and stop the CPU.
+    ///
+    /// During a fault test, exit QEMU with whether `class` was the
+    /// expected fault instead.
+    pub fn finish(mut self, class: Option<FaultClass>) -> ! {
+        // Code addresses are randomized; this maps them back to the ELF
+        self.line(format_args!(
+            "Kernel image offset: {:#x}",
+            super::vmm::kernel_image_offset()
+        ));
+        self.line(format_args!("{}", RULE));
+
+        if let Some(expected) = FaultClass::from_u8(EXPECTED.load(Ordering::SeqCst)) {
+            let passed = class == Some(expected);
+            self.line(format_args!(
+                "Fault test {}: {}",
+                expected.name(),
+                if passed { "PASSED" } else { "FAILED" }
+            ));
+            super::exit_qemu(if passed {
+                FAULT_TEST_PASSED
+            } else {
+                FAULT_TEST_FAILED
+            });
+        }
+        super::halt_loop()
+    }
+}
+
+/// Draw the screen for a fatal exception and stop.
+///
+/// `details` prints anything specific to the exception after the
+/// interrupted context and control registers.
+pub fn fatal_exception(
+    title: &str,
+    frame: &InterruptStackFrame,
+    class: Option<FaultClass>,
+    details: impl FnOnce(&mut PanicScreen),
+) -> ! {
+    let Some(mut screen) = PanicScreen::begin(title) else {
+        super::halt_loop()
+    };
+    screen.frame(frame);
+    screen.control_registers();
+    details(&mut screen);
+    screen.finish(class)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_class_names_round_trip() {
+        for class in FaultClass::ALL {
+            assert_eq!(FaultClass::from_name(class.name()), Some(class));
+            assert_eq!(FaultClass::from_u8(class as u8), Some(class));
+        }
+        assert_eq!(FaultClass::from_name("page-fault"), None);
+        assert_eq!(FaultClass::from_u8(0), None);
+    }
+}
@@ -89,6 +89,17 @@ pub fn queue_input_byte(byte: u8) {
     // If buffer is full, drop the byte (oldest data is preserved)
 }
 
+/// Release the serial port lock if it is held.
+///
+/// # Safety
+/// Only for fatal paths that never return to the interrupted code: the
+/// holder's output is cut off and it must never touch the port again.
+pub unsafe fn force_unlock() {
+    if SERIAL.is_locked() {
+        SERIAL.force_unlock();
+    }
+}
+
 /// Serial port writer for formatting
 pub struct SerialWriter;
 
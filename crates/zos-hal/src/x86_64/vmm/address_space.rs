@@ -1,6 +1,10 @@
 //! Per-process Address Space management
 //!
 //! Each process has its own virtual address space with a unique PML4 table.
+//!
+//! Mappings follow W^X: `map_page`, `map_range` and `protect` only create
+//! non-executable pages, and code is mapped through `map_code`, which fills
+//! the frames before mapping them read-execute.
 
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
@@ -8,6 +12,8 @@ use x86_64::structures::paging::PhysFrame;
 use x86_64::{PhysAddr, VirtAddr};
 
 use super::page_table::{self, PageFlags, PageTable};
+use super::stack::{self, StackFault, StackRange, USER_STACK_INITIAL_SIZE, USER_STACK_MAX_SIZE};
+use super::wx::{self, WxAudit};
 use super::{allocate_frame, free_frame, phys_to_virt, PAGE_SIZE};
 
 /// Memory protection flags
@@ -50,26 +56,21 @@ impl MemoryProtection {
         }
     }
 
-    /// Create read-write-execute protection (for code loading)
-    pub const fn read_write_execute() -> Self {
-        Self {
-            read: true,
-            write: true,
-            execute: true,
-            user: true,
-        }
-    }
-
-    /// Create kernel-only protection
+    /// Create kernel-only protection (read-write data)
     pub const fn kernel() -> Self {
         Self {
             read: true,
             write: true,
-            execute: true,
+            execute: false,
             user: false,
         }
     }
 
+    /// Whether this protection is both writable and executable
+    pub const fn is_wx(&self) -> bool {
+        self.write && self.execute
+    }
+
     /// Convert to page table flags
     pub fn to_page_flags(&self) -> u64 {
         let mut flags = PageFlags::PRESENT;
@@ -124,6 +125,8 @@ pub struct AddressSpace {
     mapped_pages: usize,
     /// Frames allocated for page tables (for cleanup)
     table_frames: Vec<PhysFrame>,
+    /// Growable stacks, each with a guard page below its limit
+    stacks: Vec<StackRange>,
 }
 
 impl AddressSpace {
@@ -145,6 +148,7 @@ impl AddressSpace {
             regions: BTreeMap::new(),
             mapped_pages: 0,
             table_frames: Vec::new(),
+            stacks: Vec::new(),
         })
     }
 
@@ -160,8 +164,13 @@ impl AddressSpace {
 
     /// Map a single page at the given virtual address
     ///
-    /// Allocates a new physical frame and maps it.
+    /// Allocates a new physical frame and maps it. The page can't be
+    /// executable; use `map_code` for that.
     pub fn map_page(&mut self, vaddr: VirtAddr, prot: MemoryProtection) -> Result<(), &'static str> {
+        if prot.execute {
+            return Err("Executable pages must be mapped with map_code");
+        }
+
         // Allocate a physical frame for the page
         let frame = allocate_frame().ok_or("Failed to allocate frame for page")?;
         let paddr = frame.start_address();
@@ -202,13 +211,52 @@ impl AddressSpace {
     /// # Arguments
     /// * `vaddr` - Starting virtual address (must be page-aligned)
     /// * `size` - Size in bytes (will be rounded up to page size)
-    /// * `prot` - Memory protection
+    /// * `prot` - Memory protection (not executable; see `map_code`)
     pub fn map_range(
         &mut self,
         vaddr: VirtAddr,
         size: usize,
         prot: MemoryProtection,
     ) -> Result<(), &'static str> {
+        if prot.execute {
+            return Err("Executable pages must be mapped with map_code");
+        }
+        self.map_filled(vaddr, size, prot, |_, _| {})
+    }
+
+    /// Map code read-execute at `vaddr` (page-aligned)
+    ///
+    /// This is the only way to create executable pages. `code` is copied in
+    /// through the physical memory map before the pages are mapped, so the
+    /// pages are never writable through this address space. Any tail of the
+    /// last page is zero.
+    pub fn map_code(&mut self, vaddr: VirtAddr, code: &[u8], user: bool) -> Result<(), &'static str> {
+        if vaddr.as_u64() % PAGE_SIZE as u64 != 0 {
+            return Err("Code must be page-aligned");
+        }
+        let prot = MemoryProtection {
+            user,
+            ..MemoryProtection::read_execute()
+        };
+        self.map_filled(vaddr, code.len().max(1), prot, |index, page| {
+            let start = index * PAGE_SIZE;
+            let chunk = &code[start..code.len().min(start + PAGE_SIZE)];
+            page[..chunk.len()].copy_from_slice(chunk);
+        })
+    }
+
+    /// Allocate zeroed frames for `size` bytes, let `fill` initialize each
+    /// one (by page index) before it is mapped with `prot`.
+    fn map_filled(
+        &mut self,
+        vaddr: VirtAddr,
+        size: usize,
+        prot: MemoryProtection,
+        mut fill: impl FnMut(usize, &mut [u8]),
+    ) -> Result<(), &'static str> {
+        if prot.is_wx() {
+            return Err("W^X: pages can't be both writable and executable");
+        }
         let num_pages = (size + PAGE_SIZE - 1) / PAGE_SIZE;
         let mut frames = Vec::with_capacity(num_pages);
         
@@ -219,10 +267,11 @@ impl AddressSpace {
             let frame = allocate_frame().ok_or("Failed to allocate frame")?;
             let paddr = frame.start_address();
             
-            // Zero the frame
+            // Zero and fill the frame through the physical memory map
             unsafe {
                 let ptr = phys_to_virt(paddr).as_mut_ptr::<u8>();
                 core::ptr::write_bytes(ptr, 0, PAGE_SIZE);
+                fill(i, core::slice::from_raw_parts_mut(ptr, PAGE_SIZE));
             }
             
             // Map in page tables
@@ -298,12 +347,19 @@ impl AddressSpace {
     }
 
     /// Change protection for a mapped range
+    ///
+    /// Can remove execute permission but never add it, so a page that was
+    /// writable can't later be run.
     pub fn protect(
         &mut self,
         vaddr: VirtAddr,
         size: usize,
         new_prot: MemoryProtection,
     ) -> Result<(), &'static str> {
+        if new_prot.execute {
+            return Err("Executable pages must be mapped with map_code");
+        }
+
         let num_pages = (size + PAGE_SIZE - 1) / PAGE_SIZE;
         
         for i in 0..num_pages {
@@ -339,6 +395,83 @@ impl AddressSpace {
     pub fn regions(&self) -> impl Iterator<Item = &MemoryRegion> {
         self.regions.values()
     }
+
+    /// Map a user stack ending at `top` (exclusive, page-aligned)
+    ///
+    /// `USER_STACK_INITIAL_SIZE` is mapped now; the rest of
+    /// `USER_STACK_MAX_SIZE` is reserved for `handle_stack_fault` to grow
+    /// into, and the page below that is left unmapped as a guard.
+    pub fn map_stack(&mut self, top: VirtAddr) -> Result<(), &'static str> {
+        let top = top.as_u64();
+        if top % PAGE_SIZE as u64 != 0 {
+            return Err("Stack top must be page-aligned");
+        }
+        let range = StackRange {
+            name: "user",
+            top,
+            mapped_bottom: top - USER_STACK_INITIAL_SIZE as u64,
+            limit_bottom: top - USER_STACK_MAX_SIZE as u64,
+        };
+
+        // The reservation and guard page must stay free of other mappings
+        let overlaps = self.regions.values().any(|region| {
+            let base = region.base.as_u64();
+            base < top && base + region.size as u64 > range.guard_page()
+        }) || self.stacks.iter().any(|s| s.guard_page() < top && range.guard_page() < s.top);
+        if overlaps {
+            return Err("Stack reservation overlaps an existing mapping");
+        }
+
+        self.map_range(
+            VirtAddr::new(range.mapped_bottom),
+            USER_STACK_INITIAL_SIZE,
+            MemoryProtection::read_write(),
+        )?;
+        self.stacks.push(range);
+        Ok(())
+    }
+
+    /// Handle a page fault at `addr` against this space's stacks
+    ///
+    /// Grows the stack if `addr` is in its reserved range. Returns the
+    /// classification so the caller can report an overflow or treat the
+    /// fault as unrelated.
+    pub fn handle_stack_fault(&mut self, addr: VirtAddr, rsp: VirtAddr) -> StackFault {
+        let fault = stack::classify(&self.stacks, addr.as_u64(), rsp.as_u64());
+        let StackFault::Grow { new_bottom } = fault else {
+            return fault;
+        };
+        let Some(index) = self.stacks.iter().position(|s| s.covers(addr.as_u64())) else {
+            return StackFault::NotStack;
+        };
+
+        let old_bottom = self.stacks[index].mapped_bottom;
+        let size = (old_bottom - new_bottom) as usize;
+        if self
+            .map_range(VirtAddr::new(new_bottom), size, MemoryProtection::read_write())
+            .is_err()
+        {
+            // Out of frames: report it as an overflow rather than retrying forever
+            let stack = self.stacks[index];
+            return StackFault::Overflow {
+                stack: stack.name,
+                size: stack.mapped_size(),
+                past_limit: 0,
+            };
+        }
+        self.stacks[index].mapped_bottom = new_bottom;
+        fault
+    }
+
+    /// Growable stacks in this address space
+    pub fn stacks(&self) -> &[StackRange] {
+        &self.stacks
+    }
+
+    /// Check this address space's page tables for W^X violations
+    pub fn audit_wx(&self) -> WxAudit {
+        wx::audit(self.pml4_phys)
+    }
 }
 
 impl Drop for AddressSpace {
@@ -30,16 +30,33 @@
 //!                        │  - Process stack                    │
 //! 0x0000_0000_0000_0000  └─────────────────────────────────────┘
 //! ```
+//!
+//! The kernel half is randomized (KASLR): the bootloader loads the kernel
+//! (a static PIE) at a random base and applies its relocations, and places
+//! the physical memory map and boot stack at random free addresses above
+//! `KERNEL_SPACE_START`. Nothing here assumes fixed kernel addresses; the
+//! physical memory offset is read from [`phys_mem_offset`] and the image
+//! slide from [`kernel_image_offset`].
+//!
+//! Every stack, kernel or user, has an unmapped guard page below it; see
+//! [`stack`] for how faults on it are reported and how user stacks grow.
+//!
+//! No page is both writable and executable; [`wx`] enforces this and can
+//! audit a set of page tables for violations.
 
 pub mod address_space;
 pub mod frame_allocator;
 pub mod page_table;
+pub mod stack;
 pub mod tlb;
+pub mod wx;
 
 pub use address_space::{AddressSpace, MemoryBacking, MemoryProtection, MemoryRegion};
 pub use frame_allocator::FrameAllocator;
 pub use page_table::{PageFlags, PageTable, PageTableEntry};
+pub use stack::{StackFault, StackRange};
 
+use core::sync::atomic::{AtomicU64, Ordering};
 use spin::Mutex;
 use x86_64::structures::paging::PhysFrame;
 use x86_64::{PhysAddr, VirtAddr};
@@ -56,8 +73,15 @@ pub const USER_SPACE_END: u64 = 0x0000_7FFF_FFFF_FFFF;
 /// Global frame allocator
 static FRAME_ALLOCATOR: Mutex<Option<FrameAllocator>> = Mutex::new(None);
 
-/// Physical memory offset (set during init)
-static mut PHYS_MEM_OFFSET: u64 = 0;
+/// Physical memory offset (set during init, randomized by the bootloader)
+static PHYS_MEM_OFFSET: AtomicU64 = AtomicU64::new(0);
+
+/// Offset of the loaded kernel image from its link address (KASLR slide)
+static KERNEL_IMAGE_OFFSET: AtomicU64 = AtomicU64::new(0);
+
+/// Lowest physical memory size the bootloader maps, so the APIC and other
+/// MMIO below 4GB is reachable even with less RAM
+const MIN_PHYS_MAP_SIZE: u64 = 1 << 32;
 
 /// Descriptor for a memory region from the bootloader
 #[derive(Debug, Clone, Copy)]
@@ -101,7 +125,14 @@ pub enum MemoryRegionKind {
 /// Must be called only once during kernel initialization.
 /// The physical_memory_offset must be valid.
 pub unsafe fn init(physical_memory_offset: u64, memory_regions: &[MemoryRegionDescriptor]) {
-    PHYS_MEM_OFFSET = physical_memory_offset;
+    // The boot config keeps the randomized map in the kernel half
+    assert!(
+        physical_memory_offset >= KERNEL_SPACE_START
+            && physical_memory_offset % PAGE_SIZE as u64 == 0,
+        "VMM: physical memory offset {:#x} must be page-aligned kernel space",
+        physical_memory_offset
+    );
+    PHYS_MEM_OFFSET.store(physical_memory_offset, Ordering::Relaxed);
     
     // Initialize frame allocator with usable regions
     let mut allocator = FrameAllocator::new();
@@ -113,16 +144,47 @@ pub unsafe fn init(physical_memory_offset: u64, memory_regions: &[MemoryRegionDe
     }
     
     *FRAME_ALLOCATOR.lock() = Some(allocator);
+
+    // W^X: make NX and read-only entries effective, then take execute
+    // permission away from the physical memory map
+    wx::enable();
+    let phys_end = memory_regions
+        .iter()
+        .map(|region| region.start + region.size)
+        .max()
+        .unwrap_or(0)
+        .max(MIN_PHYS_MAP_SIZE);
+    if let Err(e) = wx::protect_physical_map(tlb::current_cr3(), physical_memory_offset, phys_end) {
+        crate::serial_println!("VMM: Physical memory map left executable: {}", e);
+    }
+
+    #[cfg(debug_assertions)]
+    wx::report_active();
 }
 
 /// Get the physical memory offset
 pub fn phys_mem_offset() -> u64 {
-    unsafe { PHYS_MEM_OFFSET }
+    PHYS_MEM_OFFSET.load(Ordering::Relaxed)
 }
 
 /// Convert a physical address to a virtual address using the offset mapping
 pub fn phys_to_virt(phys: PhysAddr) -> VirtAddr {
-    VirtAddr::new(phys.as_u64() + unsafe { PHYS_MEM_OFFSET })
+    let offset = phys_mem_offset();
+    debug_assert!(offset != 0, "VMM: physical memory used before init");
+    VirtAddr::new(phys.as_u64() + offset)
+}
+
+/// Record how far the bootloader slid the kernel image from its link address.
+pub fn set_kernel_image_offset(offset: u64) {
+    KERNEL_IMAGE_OFFSET.store(offset, Ordering::Relaxed);
+}
+
+/// Offset of the kernel image from its link address.
+///
+/// Subtract it from a runtime code address to look the address up in the
+/// kernel ELF.
+pub fn kernel_image_offset() -> u64 {
+    KERNEL_IMAGE_OFFSET.load(Ordering::Relaxed)
 }
 
 /// Allocate a physical frame
@@ -0,0 +1,291 @@
+//! Stack guard pages and stack fault classification
+//!
+//! Every stack has an unmapped guard page directly below its lowest usable
+//! page, so running off the end raises a page fault instead of silently
+//! overwriting whatever lies below. The page fault handler checks the
+//! faulting address against the registered stacks to report an overflow
+//! precisely.
+//!
+//! # Layout
+//!
+//! ```text
+//! top          ┌──────────────────────┐
+//!              │  mapped stack pages  │
+//! mapped_bottom├──────────────────────┤
+//!              │  reserved (user only,│  grows on demand
+//!              │  mapped on fault)    │
+//! limit_bottom ├──────────────────────┤
+//!              │  guard page (never   │  fault here = overflow
+//!              │  mapped)             │
+//!              └──────────────────────┘
+//! ```
+//!
+//! Kernel stacks are fixed size (`mapped_bottom == limit_bottom`). User
+//! stacks start with `USER_STACK_INITIAL_SIZE` mapped and grow a page at a
+//! time up to `USER_STACK_MAX_SIZE`.
+
+use spin::Mutex;
+use x86_64::VirtAddr;
+
+use super::page_table;
+use super::{tlb, PAGE_SIZE};
+
+/// Size of the boot kernel stack, requested from the bootloader
+pub const KERNEL_STACK_SIZE: u64 = 128 * 1024;
+
+/// Initially mapped size of a user stack
+pub const USER_STACK_INITIAL_SIZE: usize = 16 * 1024;
+
+/// Largest size a user stack may grow to
+pub const USER_STACK_MAX_SIZE: usize = 1024 * 1024;
+
+/// Maximum number of kernel stacks that can be registered
+pub const MAX_KERNEL_STACKS: usize = 8;
+
+const PAGE: u64 = PAGE_SIZE as u64;
+
+/// Round an address down to its page.
+pub const fn page_floor(addr: u64) -> u64 {
+    addr & !(PAGE - 1)
+}
+
+/// Round an address up to the next page boundary.
+pub const fn page_ceil(addr: u64) -> u64 {
+    (addr + PAGE - 1) & !(PAGE - 1)
+}
+
+/// A stack's address range
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StackRange {
+    /// Name used in diagnostics
+    pub name: &'static str,
+    /// One past the highest usable byte
+    pub top: u64,
+    /// Lowest currently mapped address
+    pub mapped_bottom: u64,
+    /// Lowest address the stack may ever use; the guard page sits below it
+    pub limit_bottom: u64,
+}
+
+impl StackRange {
+    /// A fixed-size stack occupying `[bottom, top)`
+    pub const fn fixed(name: &'static str, bottom: u64, top: u64) -> Self {
+        Self {
+            name,
+            top,
+            mapped_bottom: bottom,
+            limit_bottom: bottom,
+        }
+    }
+
+    /// Base address of the guard page
+    pub const fn guard_page(&self) -> u64 {
+        self.limit_bottom - PAGE
+    }
+
+    /// Whether `addr` lies in the stack or its guard page
+    pub const fn covers(&self, addr: u64) -> bool {
+        addr >= self.guard_page() && addr < self.top
+    }
+
+    /// Size currently mapped
+    pub const fn mapped_size(&self) -> u64 {
+        self.top - self.mapped_bottom
+    }
+}
+
+/// What a page fault means for the stacks it was checked against
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackFault {
+    /// The access hit a stack's guard page (or ran past it)
+    Overflow {
+        /// Stack that overflowed
+        stack: &'static str,
+        /// Size of the stack, in bytes
+        size: u64,
+        /// How far below the stack's limit the access was
+        past_limit: u64,
+    },
+    /// The access is in a growable stack's reserved range
+    Grow {
+        /// New lowest mapped page
+        new_bottom: u64,
+    },
+    /// Not a known stack, but the access is just below the interrupted
+    /// stack pointer, which is how an overflow of an unknown stack looks
+    ProbableOverflow,
+    /// Not stack related
+    NotStack,
+}
+
+/// Classify a fault at `addr` taken while the stack pointer was `rsp`.
+pub fn classify(stacks: &[StackRange], addr: u64, rsp: u64) -> StackFault {
+    for stack in stacks {
+        // A push can land in the guard page, or a large frame can skip it;
+        // either way the stack pointer has left the stack from below
+        let rsp_escaped = rsp < stack.limit_bottom && rsp >= stack.guard_page();
+        if stack.covers(addr) || rsp_escaped {
+            if addr < stack.limit_bottom {
+                return StackFault::Overflow {
+                    stack: stack.name,
+                    size: stack.top - stack.limit_bottom,
+                    past_limit: stack.limit_bottom - addr,
+                };
+            }
+            if addr < stack.mapped_bottom {
+                return StackFault::Grow {
+                    new_bottom: page_floor(addr),
+                };
+            }
+            // Mapped stack page: a protection fault, not an overflow
+            return StackFault::NotStack;
+        }
+    }
+
+    if addr <= rsp && rsp - addr <= PAGE {
+        StackFault::ProbableOverflow
+    } else {
+        StackFault::NotStack
+    }
+}
+
+/// Registered kernel stacks (boot stack and interrupt stacks)
+static KERNEL_STACKS: Mutex<[Option<StackRange>; MAX_KERNEL_STACKS]> =
+    Mutex::new([None; MAX_KERNEL_STACKS]);
+
+/// Register a kernel stack for fault diagnostics.
+pub fn register_kernel_stack(range: StackRange) -> Result<(), &'static str> {
+    let mut stacks = KERNEL_STACKS.lock();
+    let slot = stacks
+        .iter_mut()
+        .find(|slot| slot.is_none())
+        .ok_or("Too many kernel stacks")?;
+    *slot = Some(range);
+    Ok(())
+}
+
+/// Unmap the guard page below a kernel stack and register the stack.
+///
+/// The stack must be page-aligned and its guard page must be backed by the
+/// kernel image (not the frame allocator), since the frame is not freed.
+///
+/// # Safety
+/// The VMM must be initialized, and nothing may live in the guard page.
+pub unsafe fn guard_kernel_stack(range: StackRange) -> Result<(), &'static str> {
+    let guard = VirtAddr::new(range.guard_page());
+    if page_table::unmap_page(tlb::current_cr3(), guard).is_none() {
+        // Already unmapped, or inside a huge page we can't split
+        crate::serial_println!(
+            "VMM: No 4KB mapping to remove for guard page of {} at {:#x}",
+            range.name,
+            guard.as_u64()
+        );
+    }
+    tlb::flush_page(guard);
+    register_kernel_stack(range)
+}
+
+/// Register the boot stack the bootloader set up.
+///
+/// The bootloader places its own guard page below the stack but doesn't
+/// report where the stack is, so the range is derived from the stack
+/// pointer during early init and the configured size. Only a few frames
+/// are live at that point, so the stack top is the next page boundary.
+pub fn register_boot_stack(rsp: u64) -> Result<(), &'static str> {
+    let top = page_ceil(rsp);
+    register_kernel_stack(StackRange::fixed("boot", top - KERNEL_STACK_SIZE, top))
+}
+
+/// Read the current stack pointer.
+#[inline(always)]
+pub fn current_stack_pointer() -> u64 {
+    let rsp: u64;
+    // SAFETY: reads a register, no memory access
+    unsafe {
+        core::arch::asm!("mov {}, rsp", out(reg) rsp, options(nomem, nostack, preserves_flags));
+    }
+    rsp
+}
+
+/// Classify a kernel-mode fault against the registered kernel stacks.
+///
+/// Called from the page fault handler, so the registry is only tried:
+/// a fault while it is locked is classified with the stack pointer alone.
+pub fn classify_kernel_fault(addr: u64, rsp: u64) -> StackFault {
+    match KERNEL_STACKS.try_lock() {
+        Some(slots) => {
+            let mut stacks = [StackRange::fixed("", 0, 0); MAX_KERNEL_STACKS];
+            let mut count = 0;
+            for range in slots.iter().flatten() {
+                stacks[count] = *range;
+                count += 1;
+            }
+            classify(&stacks[..count], addr, rsp)
+        }
+        None => classify(&[], addr, rsp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOP: u64 = 0x7000_0000;
+
+    fn user_stack() -> StackRange {
+        StackRange {
+            name: "user",
+            top: TOP,
+            mapped_bottom: TOP - USER_STACK_INITIAL_SIZE as u64,
+            limit_bottom: TOP - USER_STACK_MAX_SIZE as u64,
+        }
+    }
+
+    #[test]
+    fn test_guard_page_hit_is_overflow() {
+        let stack = StackRange::fixed("ist", 0x10_000, 0x14_000);
+        assert_eq!(
+            classify(&[stack], 0xF_FF8, 0x10_000),
+            StackFault::Overflow {
+                stack: "ist",
+                size: 0x4000,
+                past_limit: 8
+            }
+        );
+        // A frame large enough to skip the guard page is caught through rsp
+        assert!(matches!(
+            classify(&[stack], 0xE_000, 0xF_F00),
+            StackFault::Overflow { .. }
+        ));
+    }
+
+    #[test]
+    fn test_user_stack_grows_until_limit() {
+        let stack = user_stack();
+        let addr = stack.mapped_bottom - 10;
+        assert_eq!(
+            classify(&[stack], addr, addr),
+            StackFault::Grow {
+                new_bottom: page_floor(addr)
+            }
+        );
+        assert!(matches!(
+            classify(&[stack], stack.limit_bottom - 1, stack.limit_bottom),
+            StackFault::Overflow { .. }
+        ));
+    }
+
+    #[test]
+    fn test_other_faults_are_not_stack() {
+        let stack = user_stack();
+        // Mapped stack page: protection fault
+        assert_eq!(classify(&[stack], TOP - 8, TOP - 16), StackFault::NotStack);
+        // Far from any stack or the stack pointer
+        assert_eq!(classify(&[stack], 0x1000, TOP - 16), StackFault::NotStack);
+        // Just below rsp on an unregistered stack
+        assert_eq!(
+            classify(&[], 0x5000_0FF8, 0x5000_1000),
+            StackFault::ProbableOverflow
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Write-xor-execute enforcement and page permission audit
+//!
+//! No page may be both writable and executable. The kernel image already
+//! follows this (the bootloader maps each ELF segment with its own flags),
+//! so enforcement here covers the mappings the kernel itself controls:
+//!
+//! - NX and CR0.WP are switched on, so `NO_EXECUTE` and read-only entries
+//!   are honoured (including for ring 0 writes)
+//! - the physical memory map, which is pure data, is marked NX at the top
+//!   level
+//! - address spaces only create executable pages through
+//!   [`AddressSpace::map_code`](super::AddressSpace::map_code), which never
+//!   maps them writable
+//!
+//! [`audit`] walks a page table hierarchy and reports any page that is
+//! still writable and executable; debug builds run it at boot.
+
+use alloc::vec::Vec;
+use x86_64::registers::control::{Cr0, Cr0Flags, Efer, EferFlags};
+use x86_64::{PhysAddr, VirtAddr};
+
+use super::page_table::{pml4_index, PageFlags, PageTable};
+use super::phys_to_virt;
+
+/// Most violations an audit keeps; the rest are only counted
+pub const MAX_REPORTED_VIOLATIONS: usize = 16;
+
+/// Span of one entry at each level, PML4 first
+const LEVEL_SPAN: [u64; 4] = [1 << 39, 1 << 30, 1 << 21, 1 << 12];
+
+/// Effective access of a mapping, combined across all paging levels
+///
+/// A page is only writable (or user accessible) if every level allows it,
+/// and only executable if no level sets NX.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Access {
+    pub write: bool,
+    pub execute: bool,
+    pub user: bool,
+}
+
+impl Access {
+    /// Access before any level has restricted it
+    pub const ALL: Self = Self {
+        write: true,
+        execute: true,
+        user: true,
+    };
+
+    /// Restrict by one entry's flags.
+    pub const fn through(self, flags: u64) -> Self {
+        Self {
+            write: self.write && flags & PageFlags::WRITABLE != 0,
+            execute: self.execute && flags & PageFlags::NO_EXECUTE == 0,
+            user: self.user && flags & PageFlags::USER != 0,
+        }
+    }
+
+    /// Whether the mapping breaks W^X
+    pub const fn is_wx(&self) -> bool {
+        self.write && self.execute
+    }
+}
+
+/// A writable and executable mapping found by [`audit`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WxViolation {
+    /// Virtual address of the page
+    pub vaddr: u64,
+    /// Page size (4KB, 2MB or 1GB)
+    pub size: u64,
+    /// Whether user mode can reach it
+    pub user: bool,
+}
+
+/// Result of a page table audit
+#[derive(Clone, Debug, Default)]
+pub struct WxAudit {
+    /// The first `MAX_REPORTED_VIOLATIONS` violations
+    pub violations: Vec<WxViolation>,
+    /// Total number of violating pages
+    pub total: usize,
+}
+
+impl WxAudit {
+    /// Whether no violations were found
+    pub fn is_clean(&self) -> bool {
+        self.total == 0
+    }
+
+    fn record(&mut self, violation: WxViolation) {
+        if self.violations.len() < MAX_REPORTED_VIOLATIONS {
+            self.violations.push(violation);
+        }
+        self.total += 1;
+    }
+}
+
+/// Turn on NX support (EFER.NXE) and supervisor write protection (CR0.WP).
+///
+/// # Safety
+/// Must run before any entry with `NO_EXECUTE` is used; without NXE that
+/// bit is reserved and faults.
+pub unsafe fn enable() {
+    Efer::update(|flags| flags.insert(EferFlags::NO_EXECUTE_ENABLE));
+    Cr0::update(|flags| flags.insert(Cr0Flags::WRITE_PROTECT));
+}
+
+/// Mark the physical memory map `[offset, offset + size)` non-executable.
+///
+/// The bootloader gives the map PML4 entries of its own, so NX is set on
+/// those entries rather than on every page below them. An entry that also
+/// holds kernel code is left alone.
+///
+/// # Safety
+/// [`enable`] must have run, and `pml4_phys` must be the active PML4.
+pub unsafe fn protect_physical_map(
+    pml4_phys: PhysAddr,
+    offset: u64,
+    size: u64,
+) -> Result<(), &'static str> {
+    if size == 0 {
+        return Ok(());
+    }
+    let first = pml4_index(VirtAddr::new(offset));
+    let last = pml4_index(VirtAddr::new(offset + size - 1));
+    let code = pml4_index(VirtAddr::new(protect_physical_map as usize as u64));
+    if (first..=last).contains(&code) {
+        return Err("Physical memory map shares a PML4 entry with kernel code");
+    }
+
+    let pml4 = &mut *phys_to_virt(pml4_phys).as_mut_ptr::<PageTable>();
+    for index in first..=last {
+        let entry = pml4.entry_mut(index);
+        if entry.is_present() {
+            entry.set(entry.phys_addr(), entry.flags() | PageFlags::NO_EXECUTE);
+        }
+    }
+    super::tlb::flush_all();
+    Ok(())
+}
+
+/// Walk a page table hierarchy and report writable, executable pages.
+///
+/// Subtrees that are already read-only or NX are skipped, so a clean
+/// hierarchy is cheap to check.
+pub fn audit(pml4_phys: PhysAddr) -> WxAudit {
+    let mut report = WxAudit::default();
+    // SAFETY: the tables are only read, through the physical memory map
+    unsafe { audit_table(pml4_phys, 0, 0, Access::ALL, &mut report) };
+    report
+}
+
+unsafe fn audit_table(
+    table_phys: PhysAddr,
+    level: usize,
+    base: u64,
+    access: Access,
+    report: &mut WxAudit,
+) {
+    let table = &*phys_to_virt(table_phys).as_ptr::<PageTable>();
+    for (index, entry) in table.iter().enumerate() {
+        if !entry.is_present() {
+            continue;
+        }
+        let access = access.through(entry.flags());
+        if !access.is_wx() {
+            continue;
+        }
+
+        let vaddr = canonical(base + index as u64 * LEVEL_SPAN[level]);
+        // Level 0 (PML4) entries can't be huge pages
+        let leaf = level == 3 || (level > 0 && entry.is_huge());
+        if leaf {
+            report.record(WxViolation {
+                vaddr,
+                size: LEVEL_SPAN[level],
+                user: access.user,
+            });
+        } else {
+            audit_table(entry.phys_addr(), level + 1, vaddr, access, report);
+        }
+    }
+}
+
+/// Sign-extend bit 47 of a 48-bit address.
+const fn canonical(addr: u64) -> u64 {
+    (((addr << 16) as i64) >> 16) as u64
+}
+
+/// Audit the active page tables and print any violations.
+pub fn report_active() {
+    let report = audit(super::tlb::current_cr3());
+    if report.is_clean() {
+        crate::serial_println!("VMM: W^X audit clean");
+        return;
+    }
+    crate::serial_println!(
+        "VMM: W^X audit found {} writable+executable pages",
+        report.total
+    );
+    for violation in &report.violations {
+        crate::serial_println!(
+            "VMM:   {:#x} ({} bytes){}",
+            violation.vaddr,
+            violation.size,
+            if violation.user { " user" } else { "" }
+        );
+    }
+    if report.total > report.violations.len() {
+        crate::serial_println!("VMM:   ... {} more", report.total - report.violations.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RW: u64 = PageFlags::PRESENT | PageFlags::WRITABLE;
+
+    #[test]
+    fn test_nx_at_any_level_removes_execute() {
+        let leaf = Access::ALL.through(RW | PageFlags::USER);
+        assert!(leaf.is_wx());
+        let nx_above = Access::ALL
+            .through(RW | PageFlags::NO_EXECUTE)
+            .through(RW | PageFlags::USER);
+        assert!(!nx_above.execute);
+        assert!(!nx_above.is_wx());
+    }
+
+    #[test]
+    fn test_write_and_user_need_every_level() {
+        let access = Access::ALL
+            .through(RW | PageFlags::USER)
+            .through(PageFlags::PRESENT | PageFlags::USER);
+        assert!(!access.write);
+        assert!(access.user);
+        assert!(!Access::ALL.through(RW).through(RW | PageFlags::USER).user);
+    }
+
+    #[test]
+    fn test_canonical_addresses() {
+        assert_eq!(canonical(0x1000), 0x1000);
+        assert_eq!(canonical(256 << 39), 0xFFFF_8000_0000_0000);
+    }
+}
@@ -42,7 +42,7 @@ use super::serial;
 use crate::{HalError, NumericProcessHandle};
 
 pub use host::HostState;
-pub use process::{WasmProcess, ProcessState};
+pub use process::{priority, WasmProcess, ProcessState};
 
 /// Maximum syscall data buffer size (matches WASM HAL)
 pub const MAX_SYSCALL_BUFFER: usize = 16384;
@@ -171,6 +171,7 @@ impl WasmRuntime {
             pid,
             name: String::from(name),
             state: ProcessState::Ready,
+            priority: priority::initial(pid),
             store,
             instance,
             start_func,
@@ -408,6 +409,27 @@ impl WasmRuntime {
         }
     }
     
+    /// Set a process's scheduling priority class
+    pub fn set_priority(&self, pid: u64, priority: u8) {
+        if let Some(process) = self.processes.lock().get_mut(&pid) {
+            process.priority = priority;
+        }
+    }
+    
+    /// Ready processes in the order they should run: by priority class,
+    /// then PID, as (priority, pid)
+    fn ready_processes(&self) -> Vec<(u8, u64)> {
+        let mut ready: Vec<(u8, u64)> = self
+            .processes
+            .lock()
+            .iter()
+            .filter(|(_, p)| p.state == ProcessState::Ready)
+            .map(|(pid, p)| (p.priority, *pid))
+            .collect();
+        ready.sort_unstable();
+        ready
+    }
+    
     /// Check if a process is alive
     pub fn is_alive(&self, pid: u64) -> bool {
         self.processes
@@ -440,24 +462,14 @@ impl WasmRuntime {
     /// Run all ready processes and collect their syscalls
     ///
     /// This is the main scheduler entry point. It:
-    /// 1. Runs each process that is in Ready state
+    /// 1. Runs each process that is in Ready state, higher priority classes first
     /// 2. Collects any syscalls they made
     /// 3. Returns the pending syscalls for the kernel to process
     pub fn run_all_processes(&self) -> Vec<PendingSyscall> {
         let mut syscalls = Vec::new();
         
-        // Get list of PIDs to run
-        let pids: Vec<u64> = {
-            self.processes
-                .lock()
-                .iter()
-                .filter(|(_, p)| p.state == ProcessState::Ready)
-                .map(|(pid, _)| *pid)
-                .collect()
-        };
-        
-        // Run each ready process
-        for pid in pids {
+        // Run each ready process, highest priority class first
+        for (_, pid) in self.ready_processes() {
             let mut processes = self.processes.lock();
             let process = match processes.get_mut(&pid) {
                 Some(p) => p,
@@ -492,6 +504,10 @@ impl WasmRuntime {
     /// Runs multiple rounds to ensure newly spawned processes get scheduled.
     /// Each round refreshes the PID list to include processes spawned during
     /// the previous round.
+    ///
+    /// Within a round, higher priority classes run first. Background
+    /// processes get one turn per tick while anything else is ready, so
+    /// background work cannot crowd out the desktop or a service.
     pub fn run_all_processes_with_handler<F>(&self, handler: &mut F)
     where
         F: FnMut(PendingSyscall) -> (i64, Vec<u8>),
@@ -500,14 +516,11 @@ impl WasmRuntime {
         
         for round in 0..MAX_ROUNDS {
             // Refresh PID list each round to include newly spawned processes
-            let pids: Vec<u64> = {
-                self.processes
-                    .lock()
-                    .iter()
-                    .filter(|(_, p)| p.state == ProcessState::Ready)
-                    .map(|(pid, _)| *pid)
-                    .collect()
-            };
+            let mut ready = self.ready_processes();
+            if round > 0 && ready.iter().any(|(prio, _)| *prio < priority::BACKGROUND) {
+                ready.retain(|(prio, _)| *prio < priority::BACKGROUND);
+            }
+            let pids: Vec<u64> = ready.into_iter().map(|(_, pid)| pid).collect();
             
             if pids.is_empty() {
                 // No ready processes - done for this tick
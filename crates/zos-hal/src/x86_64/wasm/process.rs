@@ -20,6 +20,26 @@ pub enum ProcessState {
     Terminated,
 }
 
+/// Scheduling priority classes (mirror `zos_ipc::priority`; lower runs first)
+pub mod priority {
+    /// Core services the rest of the system waits on
+    pub const SYSTEM_SERVICE: u8 = 0;
+    /// Apps a user is looking at
+    pub const INTERACTIVE: u8 = 1;
+    /// Work nobody is waiting on
+    pub const BACKGROUND: u8 = 2;
+
+    /// The class a process starts in, as in the kernel: Init is a system
+    /// service, every other process is interactive until told otherwise
+    pub fn initial(pid: u64) -> u8 {
+        if pid <= 1 {
+            SYSTEM_SERVICE
+        } else {
+            INTERACTIVE
+        }
+    }
+}
+
 /// A running WASM process
 pub struct WasmProcess {
     /// Process ID
@@ -28,6 +48,8 @@ pub struct WasmProcess {
     pub name: String,
     /// Current state
     pub state: ProcessState,
+    /// Scheduling priority class (see [`priority`])
+    pub priority: u8,
     /// Wasmi store containing the process's host state
     pub store: Store<HostState>,
     /// Wasmi instance
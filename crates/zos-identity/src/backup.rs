@@ -0,0 +1,507 @@
+//! Neural Key shard backup helpers.
+//!
+//! Generation splits the Neural Key 3-of-5: two shards are kept encrypted in
+//! the keystore and three are handed to the user. This module supports the
+//! backup flow around those three external shards:
+//!
+//! - **Export**: a shard is encoded as a QR-friendly blob (QR alphanumeric
+//!   charset only) carrying the user ID, the shard index and a CRC-32, so a
+//!   mistyped or mis-scanned backup is caught before it is used.
+//! - **Verification**: at generation time a SHA-256 commitment of every
+//!   external shard is stored next to the encrypted shards. A shard set can
+//!   then be checked against those commitments, and against the threshold,
+//!   without ever reconstructing the key.
+//! - **Tracking**: [`ShardBackupState`] records which shards the user has
+//!   confirmed as backed up.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::ZidNeuralShard;
+use crate::error::KeyError;
+use crate::ipc::NeuralShard;
+use crate::keystore::EncryptedShardStore;
+use crate::serde_helpers::u128_hex_string;
+use crate::types::UserId;
+
+/// Number of shards needed to reconstruct a Neural Key.
+pub const SHARD_THRESHOLD: usize = 3;
+
+/// Prefix (and format version) of an exported shard blob.
+const BLOB_PREFIX: &str = "ZOS1";
+
+/// Domain separator for shard commitments.
+const COMMITMENT_DOMAIN: &[u8] = b"zos-shard-commit-v1";
+
+// ============================================================================
+// Export blobs
+// ============================================================================
+
+/// Encode a shard as a backup blob.
+///
+/// Format: `ZOS1:{user_id}:{index}:{shard}:{crc32}`, all upper-case hex, so
+/// the whole string fits the QR alphanumeric mode.
+pub fn encode_shard_blob(user_id: UserId, shard: &NeuralShard) -> String {
+    let body = format!(
+        "{}:{:032X}:{}:{}",
+        BLOB_PREFIX,
+        user_id,
+        shard.index,
+        shard.hex.to_ascii_uppercase()
+    );
+    let checksum = crc32(body.as_bytes());
+    format!("{}:{:08X}", body, checksum)
+}
+
+/// Decode a backup blob into the user it belongs to and the shard.
+///
+/// Surrounding whitespace and letter case are ignored, since blobs may be
+/// typed back in by hand.
+pub fn decode_shard_blob(blob: &str) -> Result<(UserId, NeuralShard), ShardProblem> {
+    let blob = blob.trim().to_ascii_uppercase();
+    let (body, checksum) = blob.rsplit_once(':').ok_or(ShardProblem::Malformed)?;
+    let checksum = u32::from_str_radix(checksum, 16).map_err(|_| ShardProblem::Malformed)?;
+    if crc32(body.as_bytes()) != checksum {
+        return Err(ShardProblem::ChecksumMismatch);
+    }
+
+    let mut parts = body.split(':');
+    if parts.next() != Some(BLOB_PREFIX) {
+        return Err(ShardProblem::Malformed);
+    }
+    let user_id = parts
+        .next()
+        .and_then(|s| u128::from_str_radix(s, 16).ok())
+        .ok_or(ShardProblem::Malformed)?;
+    let index = parts
+        .next()
+        .and_then(|s| s.parse::<u8>().ok())
+        .ok_or(ShardProblem::Malformed)?;
+    let hex = parts.next().ok_or(ShardProblem::Malformed)?;
+    if parts.next().is_some() {
+        return Err(ShardProblem::Malformed);
+    }
+
+    Ok((
+        user_id,
+        NeuralShard {
+            index,
+            hex: hex.to_ascii_lowercase(),
+        },
+    ))
+}
+
+/// A shard as submitted by a client: a scanned blob or an index/hex pair.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ShardInput {
+    /// Blob produced by [`encode_shard_blob`]
+    Blob(String),
+    /// Shard typed in from a paper backup
+    Shard(NeuralShard),
+}
+
+impl ShardInput {
+    /// Resolve the input to a shard, checking a blob belongs to `user_id`.
+    pub fn into_shard(self, user_id: UserId) -> Result<NeuralShard, ShardProblem> {
+        match self {
+            ShardInput::Shard(shard) => Ok(shard),
+            ShardInput::Blob(blob) => {
+                let (blob_user, shard) = decode_shard_blob(&blob)?;
+                if blob_user != user_id {
+                    return Err(ShardProblem::WrongUser);
+                }
+                Ok(shard)
+            }
+        }
+    }
+}
+
+/// CRC-32 (IEEE) used as the blob checksum.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+// ============================================================================
+// Commitments and threshold verification
+// ============================================================================
+
+/// Commitment to one shard: hex SHA-256 of domain || user_id || index || shard.
+///
+/// `user_id` is the ID stored in the [`EncryptedShardStore`] (the one used for
+/// key derivation).
+pub fn shard_commitment(user_id: UserId, shard: &NeuralShard) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(COMMITMENT_DOMAIN);
+    hasher.update(user_id.to_be_bytes());
+    hasher.update([shard.index]);
+    hasher.update(shard.hex.to_ascii_lowercase().as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Commitment recorded for one external shard.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardCommitment {
+    /// Shard index (1-5)
+    pub index: u8,
+    /// Output of [`shard_commitment`]
+    pub commitment: String,
+}
+
+/// Why a shard was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShardProblem {
+    /// Not a shard blob, or the shard data cannot be parsed
+    Malformed,
+    /// Blob checksum does not match (typo or bad scan)
+    ChecksumMismatch,
+    /// Blob was exported for another user
+    WrongUser,
+    /// Index is not one of this user's external shards
+    UnknownIndex,
+    /// Same index given more than once
+    Duplicate,
+    /// Shard does not match the commitment recorded at generation
+    CommitmentMismatch,
+}
+
+/// A shard rejected by [`check_shard_set`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectedShard {
+    /// Position of the shard in the submitted set
+    pub position: usize,
+    /// Shard index, when it could be read
+    pub index: Option<u8>,
+    /// Reason for rejection
+    pub problem: ShardProblem,
+}
+
+/// Outcome of checking a shard set against the threshold.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardSetReport {
+    /// Distinct shard indices that passed every check (sorted)
+    pub valid_indices: Vec<u8>,
+    /// Shards that were rejected
+    pub rejected: Vec<RejectedShard>,
+    /// Whether shards were checked against stored commitments. False for keys
+    /// generated before commitments were recorded, in which case only the
+    /// format and indices could be checked.
+    pub authenticated: bool,
+    /// The valid shards alone are enough to recover the key
+    pub meets_threshold: bool,
+    /// The valid shards plus the password-protected shards are enough
+    pub meets_threshold_with_password: bool,
+}
+
+/// Check a shard set without reconstructing the Neural Key.
+///
+/// Each entry is either a decoded shard or the problem found while decoding
+/// it. Shards must be well-formed, belong to one of the store's external
+/// indices, not repeat an index, and match the stored commitment when the
+/// store has one.
+pub fn check_shard_set(
+    shards: &[Result<NeuralShard, ShardProblem>],
+    store: &EncryptedShardStore,
+) -> ShardSetReport {
+    let authenticated = !store.external_shard_commitments.is_empty();
+    let mut valid_indices: Vec<u8> = Vec::new();
+    let mut rejected = Vec::new();
+
+    for (position, shard) in shards.iter().enumerate() {
+        let checked = shard
+            .as_ref()
+            .map_err(|problem| *problem)
+            .and_then(|s| check_shard(s, store, &valid_indices).map(|()| s.index));
+        match checked {
+            Ok(index) => valid_indices.push(index),
+            Err(problem) => rejected.push(RejectedShard {
+                position,
+                index: shard.as_ref().ok().map(|s| s.index),
+                problem,
+            }),
+        }
+    }
+    valid_indices.sort_unstable();
+
+    let with_password = valid_indices.len() + store.encrypted_shards.len();
+    ShardSetReport {
+        meets_threshold: valid_indices.len() >= SHARD_THRESHOLD,
+        meets_threshold_with_password: !valid_indices.is_empty()
+            && with_password >= SHARD_THRESHOLD,
+        valid_indices,
+        rejected,
+        authenticated,
+    }
+}
+
+/// Check a single external shard against the store.
+pub fn check_shard(
+    shard: &NeuralShard,
+    store: &EncryptedShardStore,
+    seen: &[u8],
+) -> Result<(), ShardProblem> {
+    if !store.external_shard_indices.contains(&shard.index) {
+        return Err(ShardProblem::UnknownIndex);
+    }
+    if seen.contains(&shard.index) {
+        return Err(ShardProblem::Duplicate);
+    }
+    if ZidNeuralShard::from_hex(&shard.hex).is_err() {
+        return Err(ShardProblem::Malformed);
+    }
+    if let Some(expected) = store
+        .external_shard_commitments
+        .iter()
+        .find(|c| c.index == shard.index)
+    {
+        if shard_commitment(store.user_id, shard) != expected.commitment {
+            return Err(ShardProblem::CommitmentMismatch);
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Backup tracking
+// ============================================================================
+
+/// Which external shards the user has confirmed as backed up.
+///
+/// Path: `/keys/{user_id}/identity/shard_backup.json` (keystore)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ShardBackupState {
+    /// Confirmed shards, one entry per index
+    pub backed_up: Vec<ShardBackupEntry>,
+}
+
+/// A shard confirmed as backed up.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardBackupEntry {
+    /// Shard index (1-5)
+    pub index: u8,
+    /// When the backup was confirmed (Unix timestamp ms)
+    pub backed_up_at: u64,
+}
+
+impl ShardBackupState {
+    /// Keystore path for a user's backup state.
+    pub fn storage_path(user_id: UserId) -> String {
+        format!("/keys/{}/identity/shard_backup.json", user_id)
+    }
+
+    /// Record `index` as backed up. Re-confirming keeps the original time.
+    pub fn mark_backed_up(&mut self, index: u8, now_ms: u64) {
+        if !self.backed_up.iter().any(|e| e.index == index) {
+            self.backed_up.push(ShardBackupEntry {
+                index,
+                backed_up_at: now_ms,
+            });
+            self.backed_up.sort_unstable_by_key(|e| e.index);
+        }
+    }
+
+    /// Summarise the state against the user's external shard indices.
+    pub fn status(&self, user_id: UserId, store: &EncryptedShardStore) -> ShardBackupStatus {
+        let shards: Vec<ShardBackupStatusEntry> = store
+            .external_shard_indices
+            .iter()
+            .map(|&index| ShardBackupStatusEntry {
+                index,
+                backed_up_at: self
+                    .backed_up
+                    .iter()
+                    .find(|e| e.index == index)
+                    .map(|e| e.backed_up_at),
+            })
+            .collect();
+        let backed_up = shards.iter().filter(|s| s.backed_up_at.is_some()).count();
+        ShardBackupStatus {
+            user_id,
+            complete: backed_up == shards.len(),
+            recoverable_without_password: backed_up >= SHARD_THRESHOLD,
+            shards,
+        }
+    }
+}
+
+/// Backup status of a user's external shards.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShardBackupStatus {
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+    /// One entry per external shard
+    pub shards: Vec<ShardBackupStatusEntry>,
+    /// Every external shard has been backed up
+    pub complete: bool,
+    /// Enough shards are backed up to recover without the password
+    pub recoverable_without_password: bool,
+}
+
+/// Backup status of one external shard.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShardBackupStatusEntry {
+    /// Shard index (1-5)
+    pub index: u8,
+    /// When the backup was confirmed, if it has been
+    pub backed_up_at: Option<u64>,
+}
+
+/// Map a shard problem onto the key error returned to single-shard callers.
+impl From<ShardProblem> for KeyError {
+    fn from(problem: ShardProblem) -> Self {
+        let reason = match problem {
+            ShardProblem::Malformed => "shard is malformed",
+            ShardProblem::ChecksumMismatch => "shard checksum does not match",
+            ShardProblem::WrongUser => "shard belongs to another user",
+            ShardProblem::UnknownIndex => "shard index is not one of your backup shards",
+            ShardProblem::Duplicate => "shard index given more than once",
+            ShardProblem::CommitmentMismatch => "shard does not match your Neural Key",
+        };
+        KeyError::InvalidShard(reason.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keystore::{EncryptedShard, KeyDerivation};
+
+    fn shard(index: u8) -> NeuralShard {
+        NeuralShard {
+            index,
+            hex: format!("{:02x}abcdef0123", index),
+        }
+    }
+
+    fn store() -> EncryptedShardStore {
+        EncryptedShardStore {
+            user_id: 0x42,
+            encrypted_shards: [2u8, 4]
+                .iter()
+                .map(|&index| EncryptedShard {
+                    index,
+                    ciphertext: Vec::new(),
+                    nonce: [0; 12],
+                    tag: [0; 16],
+                })
+                .collect(),
+            external_shard_indices: alloc::vec![1, 3, 5],
+            external_shard_commitments: [1u8, 3, 5]
+                .iter()
+                .map(|&index| ShardCommitment {
+                    index,
+                    commitment: shard_commitment(0x42, &shard(index)),
+                })
+                .collect(),
+            kdf: KeyDerivation {
+                algorithm: String::from("Argon2id"),
+                salt: [0; 32],
+                time_cost: 1,
+                memory_cost: 64,
+                parallelism: 1,
+            },
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_blob_roundtrip_and_checksum() {
+        let blob = encode_shard_blob(0x99, &shard(3));
+        assert!(blob
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase() || c == ':'));
+
+        let (user_id, decoded) = decode_shard_blob(&blob.to_ascii_lowercase()).unwrap();
+        assert_eq!(user_id, 0x99);
+        assert_eq!(decoded.index, 3);
+        assert_eq!(decoded.hex, shard(3).hex);
+
+        let input: ShardInput = serde_json::from_str(&format!("\"{}\"", blob)).unwrap();
+        assert_eq!(
+            input.clone().into_shard(0x98).err(),
+            Some(ShardProblem::WrongUser)
+        );
+        assert_eq!(input.into_shard(0x99).unwrap().index, 3);
+
+        let typo = blob.replacen("ABCDEF", "ABCDEE", 1);
+        assert_eq!(
+            decode_shard_blob(&typo).err(),
+            Some(ShardProblem::ChecksumMismatch)
+        );
+        assert_eq!(
+            decode_shard_blob("not a shard").err(),
+            Some(ShardProblem::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_check_shard_set() {
+        let store = store();
+        let mut tampered = shard(5);
+        tampered.hex.push('0');
+
+        let report = check_shard_set(
+            &[
+                Ok(shard(1)),
+                Ok(shard(3)),
+                Ok(shard(1)),
+                Ok(shard(2)),
+                Ok(tampered),
+                Err(ShardProblem::ChecksumMismatch),
+            ],
+            &store,
+        );
+        assert!(report.authenticated);
+        assert_eq!(report.valid_indices, alloc::vec![1, 3]);
+        assert!(!report.meets_threshold);
+        assert!(report.meets_threshold_with_password);
+        let problems: Vec<ShardProblem> = report.rejected.iter().map(|r| r.problem).collect();
+        assert_eq!(
+            problems,
+            alloc::vec![
+                ShardProblem::Duplicate,
+                ShardProblem::UnknownIndex,
+                ShardProblem::CommitmentMismatch,
+                ShardProblem::ChecksumMismatch,
+            ]
+        );
+
+        let report = check_shard_set(&[Ok(shard(5)), Ok(shard(3)), Ok(shard(1))], &store);
+        assert!(report.meets_threshold);
+        assert!(report.rejected.is_empty());
+    }
+
+    #[test]
+    fn test_backup_status() {
+        let store = store();
+        let mut state = ShardBackupState::default();
+        state.mark_backed_up(3, 100);
+        state.mark_backed_up(3, 200);
+        state.mark_backed_up(1, 300);
+
+        let status = state.status(0x42, &store);
+        assert_eq!(status.shards.len(), 3);
+        assert_eq!(status.shards[1].backed_up_at, Some(100));
+        assert!(!status.complete);
+        assert!(!status.recoverable_without_password);
+
+        state.mark_backed_up(5, 400);
+        assert!(state.status(0x42, &store).complete);
+    }
+}
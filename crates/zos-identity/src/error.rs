@@ -133,6 +133,14 @@ pub enum ZidError {
     SessionExpired,
     /// Invalid or expired refresh token
     InvalidRefreshToken,
+    /// Enrollment request not yet approved or rejected
+    EnrollmentPending,
+    /// Enrollment request rejected by an enrolled machine
+    EnrollmentRejected,
+    /// Enrollment request expired before it was decided
+    EnrollmentExpired,
+    /// Enrollment request not found (never submitted, or already completed)
+    EnrollmentNotFound,
 }
 
 /// General identity layer error.
@@ -0,0 +1,91 @@
+//! Public key fingerprints.
+//!
+//! A fingerprint is a short digest of a public key that two people can read
+//! to each other over another channel (in person, on a call) to confirm they
+//! hold the same key. It is the first 20 bytes of the key's SHA-256 digest,
+//! written as ten groups of four uppercase hex digits:
+//!
+//! ```text
+//! 3F2A 91C0 77BE 0D14 A5E2 6C09 B1F3 4D78 02AE 9C55
+//! ```
+//!
+//! Fingerprints typed back in may use any case, spaces or colons.
+
+use alloc::string::String;
+use sha2::{Digest, Sha256};
+
+/// Bytes of the digest kept in a fingerprint.
+pub const FINGERPRINT_BYTES: usize = 20;
+
+/// Fingerprint of a 32-byte public key (Ed25519 or X25519).
+pub fn key_fingerprint(public_key: &[u8; 32]) -> String {
+    const HEX_CHARS: &[u8; 16] = b"0123456789ABCDEF";
+    let digest = Sha256::digest(public_key);
+    let mut text = String::with_capacity(FINGERPRINT_BYTES * 2 + FINGERPRINT_BYTES / 2);
+    for (i, byte) in digest[..FINGERPRINT_BYTES].iter().enumerate() {
+        if i > 0 && i % 2 == 0 {
+            text.push(' ');
+        }
+        text.push(HEX_CHARS[(byte >> 4) as usize] as char);
+        text.push(HEX_CHARS[(byte & 0x0F) as usize] as char);
+    }
+    text
+}
+
+/// Canonical form of a fingerprint typed in by a user, or `None` if it is
+/// not one.
+pub fn normalize_fingerprint(text: &str) -> Option<String> {
+    let mut digits = String::with_capacity(FINGERPRINT_BYTES * 2);
+    for c in text.chars() {
+        match c {
+            ' ' | ':' | '-' => {}
+            c if c.is_ascii_hexdigit() => digits.push(c.to_ascii_uppercase()),
+            _ => return None,
+        }
+    }
+    if digits.len() != FINGERPRINT_BYTES * 2 {
+        return None;
+    }
+    let mut canonical = String::with_capacity(FINGERPRINT_BYTES * 2 + FINGERPRINT_BYTES / 2);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && i % 4 == 0 {
+            canonical.push(' ');
+        }
+        canonical.push(c);
+    }
+    Some(canonical)
+}
+
+/// Whether `text` is the fingerprint of `public_key`.
+pub fn fingerprint_matches(public_key: &[u8; 32], text: &str) -> bool {
+    normalize_fingerprint(text).is_some_and(|f| f == key_fingerprint(public_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_format() {
+        let fingerprint = key_fingerprint(&[7u8; 32]);
+        assert_eq!(fingerprint.len(), 49);
+        assert_eq!(fingerprint.split(' ').count(), 10);
+        assert!(fingerprint
+            .chars()
+            .all(|c| c == ' ' || c.is_ascii_digit() || c.is_ascii_uppercase()));
+        assert_ne!(fingerprint, key_fingerprint(&[8u8; 32]));
+    }
+
+    #[test]
+    fn test_typed_fingerprints_match() {
+        let key = [42u8; 32];
+        let fingerprint = key_fingerprint(&key);
+        let typed = fingerprint.to_lowercase().replace(' ', ":");
+        assert!(fingerprint_matches(&key, &typed));
+        assert!(fingerprint_matches(&key, &fingerprint.replace(' ', "")));
+        assert!(!fingerprint_matches(&[43u8; 32], &fingerprint));
+
+        assert!(normalize_fingerprint("12AB").is_none());
+        assert!(normalize_fingerprint(&"Z".repeat(40)).is_none());
+    }
+}
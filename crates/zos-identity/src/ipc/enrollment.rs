@@ -0,0 +1,332 @@
+//! Machine Enrollment Approval Types
+//!
+//! Adding a machine to an existing identity is a handshake between the new
+//! machine and one that is already enrolled:
+//!
+//! 1. The new machine generates its keypair, keeps the secret seeds in its
+//!    keystore and posts a [`PendingEnrollment`] to the ZID server.
+//! 2. An enrolled machine lists pending requests and approves or rejects
+//!    one, signing an [`EnrollmentDecision`] with its own machine key.
+//! 3. The new machine collects the decision, checks the signature covers
+//!    its own request, and only then stores its machine key record.
+//!
+//! Requests expire after [`ENROLLMENT_TTL_MS`] whether or not they were
+//! decided.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZidError;
+use crate::keystore::{KeyScheme, MachineKeyCapabilities, MachineKeyRecord};
+use crate::serde_helpers::u128_hex_string;
+use crate::types::UserId;
+
+extern crate alloc;
+
+/// How long an enrollment request stays open (15 minutes).
+pub const ENROLLMENT_TTL_MS: u64 = 15 * 60 * 1000;
+
+/// Domain separator for signed enrollment decisions.
+const DECISION_DOMAIN: &[u8] = b"zos-enroll-decision-v1";
+
+// ============================================================================
+// Shared Types
+// ============================================================================
+
+/// An enrollment request, as seen by both machines and the ZID server.
+///
+/// Contains only public material; the seeds stay with [`LocalEnrollment`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingEnrollment {
+    /// Random request identifier (hex string for JavaScript interop)
+    #[serde(with = "u128_hex_string")]
+    pub request_id: u128,
+    /// Machine ID the new machine will use once enrolled
+    #[serde(with = "u128_hex_string")]
+    pub machine_id: u128,
+    /// New machine's signing public key (Ed25519)
+    pub signing_public_key: [u8; 32],
+    /// New machine's encryption public key (X25519)
+    pub encryption_public_key: [u8; 32],
+    /// Human-readable machine name shown to the approver
+    pub machine_name: Option<String>,
+    /// Capabilities the new machine asks for
+    pub capabilities: MachineKeyCapabilities,
+    /// When the request was made (Unix timestamp ms)
+    pub created_at: u64,
+    /// When the request lapses (Unix timestamp ms)
+    pub expires_at: u64,
+}
+
+impl PendingEnrollment {
+    /// Whether the request has lapsed at `now_ms`.
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        now_ms >= self.expires_at
+    }
+
+    /// Canonical bytes signed by the approving machine.
+    ///
+    /// Binds the decision to this request's keys, so an approval cannot be
+    /// replayed for a different keypair:
+    /// domain || outcome || request_id || machine_id || signing_pk ||
+    /// encryption_pk || expires_at || approver_machine_id || decided_at
+    pub fn decision_message(
+        &self,
+        outcome: EnrollmentOutcome,
+        approver_machine_id: u128,
+        decided_at: u64,
+    ) -> Vec<u8> {
+        let mut message = Vec::with_capacity(DECISION_DOMAIN.len() + 1 + 16 * 3 + 32 * 2 + 8 * 2);
+        message.extend_from_slice(DECISION_DOMAIN);
+        message.push(outcome as u8);
+        message.extend_from_slice(&self.request_id.to_be_bytes());
+        message.extend_from_slice(&self.machine_id.to_be_bytes());
+        message.extend_from_slice(&self.signing_public_key);
+        message.extend_from_slice(&self.encryption_public_key);
+        message.extend_from_slice(&self.expires_at.to_be_bytes());
+        message.extend_from_slice(&approver_machine_id.to_be_bytes());
+        message.extend_from_slice(&decided_at.to_be_bytes());
+        message
+    }
+}
+
+/// Outcome chosen by the approving machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrollmentOutcome {
+    Approve = 1,
+    Reject = 2,
+}
+
+/// Signed decision on an enrollment request (relayed by the ZID server).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnrollmentDecision {
+    /// Request being decided
+    #[serde(with = "u128_hex_string")]
+    pub request_id: u128,
+    /// Approve or reject
+    pub outcome: EnrollmentOutcome,
+    /// Enrolled machine that made the decision
+    #[serde(with = "u128_hex_string")]
+    pub approver_machine_id: u128,
+    /// Approver's signing public key (Ed25519)
+    pub approver_signing_public_key: [u8; 32],
+    /// When the decision was made (Unix timestamp ms)
+    pub decided_at: u64,
+    /// Ed25519 signature over [`PendingEnrollment::decision_message`] (hex)
+    pub signature: String,
+}
+
+/// Enrollment request as kept by the requesting machine until it is decided.
+///
+/// Path: `/keys/{user_id}/identity/enrollment/{request_id}.json` (keystore)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LocalEnrollment {
+    /// The request as submitted
+    pub enrollment: PendingEnrollment,
+    /// ZID endpoint the request was submitted to
+    pub zid_endpoint: String,
+    /// Signing secret seed for the new machine key
+    pub signing_sk: [u8; 32],
+    /// Encryption secret seed for the new machine key
+    pub encryption_sk: [u8; 32],
+}
+
+impl LocalEnrollment {
+    /// Keystore path for a pending request.
+    pub fn storage_path(user_id: UserId, request_id: u128) -> String {
+        alloc::format!(
+            "/keys/{}/identity/enrollment/{:032x}.json",
+            user_id,
+            request_id
+        )
+    }
+
+    /// Machine key record to store once `decision` has approved the request.
+    pub fn into_machine_key(self, decision: &EnrollmentDecision) -> MachineKeyRecord {
+        let enrollment = self.enrollment;
+        MachineKeyRecord {
+            machine_id: enrollment.machine_id,
+            signing_public_key: enrollment.signing_public_key,
+            encryption_public_key: enrollment.encryption_public_key,
+            signing_sk: Some(self.signing_sk),
+            encryption_sk: Some(self.encryption_sk),
+            authorized_at: decision.decided_at,
+            authorized_by: decision.approver_machine_id,
+            capabilities: enrollment.capabilities,
+            machine_name: enrollment.machine_name,
+            last_seen_at: decision.decided_at,
+            epoch: 1,
+            key_scheme: KeyScheme::Classical,
+            pq_signing_public_key: None,
+            pq_encryption_public_key: None,
+        }
+    }
+}
+
+/// Status of an enrollment request as reported by the ZID server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum EnrollmentStatus {
+    /// No decision yet
+    Pending,
+    /// Decided; the decision still has to be verified
+    Decided { decision: EnrollmentDecision },
+    /// Lapsed without a decision
+    Expired,
+}
+
+// ============================================================================
+// IPC Request/Response Types
+// ============================================================================
+
+/// Request enrollment of this machine into an existing identity.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestEnrollmentRequest {
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+    /// ZID API endpoint (e.g., "https://api.zero-id.io")
+    pub zid_endpoint: String,
+    /// Optional human-readable machine name
+    pub machine_name: Option<String>,
+    /// Capabilities to ask for
+    #[serde(default)]
+    pub capabilities: MachineKeyCapabilities,
+}
+
+/// Request enrollment response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestEnrollmentResponse {
+    /// The submitted request (show its ID to the user so they can match it
+    /// on the approving machine)
+    pub result: Result<PendingEnrollment, ZidError>,
+}
+
+/// List requests awaiting a decision, from an enrolled machine.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ListPendingEnrollmentsRequest {
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+    /// ZID API endpoint
+    pub zid_endpoint: String,
+    /// Access token of the enrolled machine's ZID session
+    pub access_token: String,
+}
+
+/// List pending enrollments response (expired requests are omitted).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ListPendingEnrollmentsResponse {
+    pub result: Result<Vec<PendingEnrollment>, ZidError>,
+}
+
+/// Approve or reject a request, signing with an enrolled machine key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DecideEnrollmentRequest {
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+    /// ZID API endpoint
+    pub zid_endpoint: String,
+    /// Local machine key to sign with (must be able to authorize machines)
+    #[serde(with = "u128_hex_string")]
+    pub approver_machine_id: u128,
+    /// The request being decided, as returned by the pending list
+    pub enrollment: PendingEnrollment,
+    /// Approve or reject
+    pub outcome: EnrollmentOutcome,
+}
+
+/// Decide enrollment response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DecideEnrollmentResponse {
+    pub result: Result<(), ZidError>,
+}
+
+/// Collect the decision on this machine's request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompleteEnrollmentRequest {
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+    /// Request returned by MSG_REQUEST_ENROLLMENT
+    #[serde(with = "u128_hex_string")]
+    pub request_id: u128,
+}
+
+/// Complete enrollment response.
+///
+/// `EnrollmentPending` means no decision yet; ask again later.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompleteEnrollmentResponse {
+    /// The newly stored machine key record on approval
+    pub result: Result<MachineKeyRecord, ZidError>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enrollment() -> PendingEnrollment {
+        PendingEnrollment {
+            request_id: 0x11,
+            machine_id: 0x22,
+            signing_public_key: [3; 32],
+            encryption_public_key: [4; 32],
+            machine_name: None,
+            capabilities: MachineKeyCapabilities::default(),
+            created_at: 1_000,
+            expires_at: 1_000 + ENROLLMENT_TTL_MS,
+        }
+    }
+
+    #[test]
+    fn test_decision_message_binds_request() {
+        let request = enrollment();
+        let approve = request.decision_message(EnrollmentOutcome::Approve, 7, 2_000);
+        assert_ne!(
+            approve,
+            request.decision_message(EnrollmentOutcome::Reject, 7, 2_000)
+        );
+
+        let mut other_key = request.clone();
+        other_key.signing_public_key = [9; 32];
+        assert_ne!(
+            approve,
+            other_key.decision_message(EnrollmentOutcome::Approve, 7, 2_000)
+        );
+
+        assert!(!request.is_expired(request.expires_at - 1));
+        assert!(request.is_expired(request.expires_at));
+    }
+
+    #[test]
+    fn test_status_serialization() {
+        let status: EnrollmentStatus = serde_json::from_str(r#"{"status":"pending"}"#).unwrap();
+        assert!(matches!(status, EnrollmentStatus::Pending));
+
+        let local = LocalEnrollment {
+            enrollment: enrollment(),
+            zid_endpoint: String::from("https://zid.test"),
+            signing_sk: [5; 32],
+            encryption_sk: [6; 32],
+        };
+        let decision = EnrollmentDecision {
+            request_id: 0x11,
+            outcome: EnrollmentOutcome::Approve,
+            approver_machine_id: 7,
+            approver_signing_public_key: [8; 32],
+            decided_at: 2_000,
+            signature: String::new(),
+        };
+        let json = serde_json::to_string(&EnrollmentStatus::Decided {
+            decision: decision.clone(),
+        })
+        .unwrap();
+        assert!(json.contains(r#""status":"decided""#));
+        assert!(json.contains(r#""outcome":"approve""#));
+
+        let record = local.into_machine_key(&decision);
+        assert_eq!(record.machine_id, 0x22);
+        assert_eq!(record.authorized_by, 7);
+        assert_eq!(record.signing_sk, Some([5; 32]));
+    }
+}
@@ -6,6 +6,7 @@ use alloc::string::String;
 use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
+use crate::backup::{ShardBackupStatus, ShardInput, ShardSetReport};
 use crate::error::KeyError;
 use crate::keystore::{KeyScheme, LocalKeyStore, MachineKeyCapabilities, MachineKeyRecord};
 use crate::serde_helpers::u128_hex_string;
@@ -93,6 +94,79 @@ pub struct RecoverNeuralKeyResponse {
     pub result: Result<NeuralKeyGenerated, KeyError>,
 }
 
+// ============================================================================
+// Shard Backup Request/Response Types
+// ============================================================================
+
+/// Export one of the user's external shards as a QR-encodable blob.
+///
+/// The shard is checked against the commitment recorded at generation, so
+/// only a genuine shard can be exported.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportShardRequest {
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+    /// Shard to export, as returned by generation
+    pub shard: NeuralShard,
+}
+
+/// Export shard response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportShardResponse {
+    /// The blob to render as a QR code or print
+    pub result: Result<String, KeyError>,
+}
+
+/// Check whether a set of backup shards reaches the recovery threshold.
+///
+/// The Neural Key is never reconstructed; shards are checked against the
+/// stored commitments only.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerifyShardsRequest {
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+    /// Scanned blobs and/or typed-in shards
+    pub shards: Vec<ShardInput>,
+}
+
+/// Verify shards response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerifyShardsResponse {
+    pub result: Result<ShardSetReport, KeyError>,
+}
+
+/// Confirm that the user has backed up a shard.
+///
+/// The shard itself is required (typically by scanning the printed QR code)
+/// so a backup is only recorded once it has been read back successfully.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MarkShardBackedUpRequest {
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+    /// The backed-up shard
+    pub shard: ShardInput,
+}
+
+/// Mark shard backed up response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MarkShardBackedUpResponse {
+    /// Updated backup status
+    pub result: Result<ShardBackupStatus, KeyError>,
+}
+
+/// Get which external shards have been backed up.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetShardBackupStatusRequest {
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+}
+
+/// Get shard backup status response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetShardBackupStatusResponse {
+    pub result: Result<ShardBackupStatus, KeyError>,
+}
+
 // ============================================================================
 // Identity Key Registration Request/Response Types
 // ============================================================================
@@ -136,6 +210,38 @@ pub struct GetIdentityKeyResponse {
     pub result: Result<Option<LocalKeyStore>, KeyError>,
 }
 
+/// Get key fingerprint request.
+///
+/// Only public keys are returned, so any process may ask; this is how the
+/// address book gets the card a user shares with their contacts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetKeyFingerprintRequest {
+    /// User ID whose identity key to fingerprint
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+}
+
+/// A user's public keys and the fingerprint of their identity key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyFingerprint {
+    /// User ID the keys belong to
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+    /// Identity-level signing public key (Ed25519, hex string)
+    pub identity_signing_pub_key: String,
+    /// Machine-level encryption public key (X25519, hex string)
+    pub machine_encryption_pub_key: String,
+    /// Fingerprint of the identity signing key (see [`crate::fingerprint`])
+    pub fingerprint: String,
+}
+
+/// Get key fingerprint response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetKeyFingerprintResponse {
+    /// Result containing the keys, or `KeysNotFound`
+    pub result: Result<KeyFingerprint, KeyError>,
+}
+
 // ============================================================================
 // Machine Key Request/Response Types
 // ============================================================================
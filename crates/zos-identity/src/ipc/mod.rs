@@ -8,6 +8,7 @@ mod session;
 mod credentials;
 mod keys;
 mod zid;
+mod enrollment;
 
 // Re-export all types for backward compatibility
 pub use user::*;
@@ -15,3 +16,4 @@ pub use session::*;
 pub use credentials::*;
 pub use keys::*;
 pub use zid::*;
+pub use enrollment::*;
@@ -185,6 +185,9 @@ pub struct IdentityPreferences {
     /// Used by ZID login to determine which machine key to authenticate with.
     #[serde(default)]
     pub default_machine_id: Option<u128>,
+    /// Apps launched when a session starts, in launch order
+    #[serde(default)]
+    pub login_items: Vec<LoginItem>,
 }
 
 impl Default for IdentityPreferences {
@@ -192,10 +195,60 @@ impl Default for IdentityPreferences {
         Self {
             default_key_scheme: KeyScheme::Classical,
             default_machine_id: None,
+            login_items: Vec::new(),
         }
     }
 }
 
+/// Maximum login items per user.
+pub const MAX_LOGIN_ITEMS: usize = 16;
+
+/// Maximum delay of a login item after session start.
+pub const MAX_LOGIN_ITEM_DELAY_MS: u32 = 60_000;
+
+/// An app launched automatically after session start.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginItem {
+    /// App to launch (e.g. "terminal")
+    pub app_id: String,
+    /// Disabled items stay in the list but are not launched
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Delay after the session's core services are ready
+    #[serde(default)]
+    pub delay_ms: u32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Check a login item list before storing it.
+///
+/// Rejects lists longer than [`MAX_LOGIN_ITEMS`], empty or repeated app
+/// IDs, and delays over [`MAX_LOGIN_ITEM_DELAY_MS`].
+pub fn validate_login_items(items: &[LoginItem]) -> Result<(), crate::KeyError> {
+    let invalid = |reason: String| Err(crate::KeyError::InvalidRequest(reason));
+    if items.len() > MAX_LOGIN_ITEMS {
+        return invalid(alloc::format!("at most {} login items", MAX_LOGIN_ITEMS));
+    }
+    for (i, item) in items.iter().enumerate() {
+        if item.app_id.is_empty() {
+            return invalid(String::from("login item without app_id"));
+        }
+        if items[..i].iter().any(|other| other.app_id == item.app_id) {
+            return invalid(alloc::format!("duplicate login item: {}", item.app_id));
+        }
+        if item.delay_ms > MAX_LOGIN_ITEM_DELAY_MS {
+            return invalid(alloc::format!(
+                "login item delay over {} ms: {}",
+                MAX_LOGIN_ITEM_DELAY_MS, item.app_id
+            ));
+        }
+    }
+    Ok(())
+}
+
 impl IdentityPreferences {
     /// VFS path where preferences are stored
     pub fn storage_path(user_id: UserId) -> String {
@@ -249,6 +302,22 @@ pub struct SetDefaultMachineKeyResponse {
     pub result: Result<(), crate::KeyError>,
 }
 
+/// Set login items request.
+///
+/// Replaces the user's whole login item list.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetLoginItemsRequest {
+    #[serde(with = "u128_hex_string")]
+    pub user_id: UserId,
+    pub items: Vec<LoginItem>,
+}
+
+/// Set login items response
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetLoginItemsResponse {
+    pub result: Result<(), crate::KeyError>,
+}
+
 // ============================================================================
 // ZID Token Refresh
 // ============================================================================
@@ -454,4 +523,41 @@ mod tests {
         assert!(json.contains("\"identity_id\""));
         assert!(json.contains("550e8400-e29b-41d4-a716-446655440000"));
     }
+
+    #[test]
+    fn test_login_items_default_and_validation() {
+        // Preferences stored before login items existed still load
+        let prefs: IdentityPreferences =
+            serde_json::from_str(r#"{"default_key_scheme":"classical"}"#).unwrap();
+        assert!(prefs.login_items.is_empty());
+
+        let item: LoginItem = serde_json::from_str(r#"{"app_id":"terminal"}"#).unwrap();
+        assert!(item.enabled);
+        assert_eq!(item.delay_ms, 0);
+
+        let notes = LoginItem {
+            app_id: "notes".to_string(),
+            enabled: false,
+            delay_ms: 2_000,
+        };
+        assert!(validate_login_items(&[item.clone(), notes.clone()]).is_ok());
+        assert!(validate_login_items(&[item.clone(), item.clone()]).is_err());
+        let slow = LoginItem {
+            delay_ms: MAX_LOGIN_ITEM_DELAY_MS + 1,
+            ..notes
+        };
+        assert!(validate_login_items(&[slow]).is_err());
+        let unnamed = LoginItem {
+            app_id: String::new(),
+            ..item.clone()
+        };
+        assert!(validate_login_items(&[unnamed]).is_err());
+        let too_many: Vec<LoginItem> = (0..=MAX_LOGIN_ITEMS)
+            .map(|i| LoginItem {
+                app_id: alloc::format!("app{}", i),
+                ..item.clone()
+            })
+            .collect();
+        assert!(validate_login_items(&too_many).is_err());
+    }
 }
@@ -27,6 +27,7 @@ use alloc::vec;
 use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
+use crate::backup::ShardCommitment;
 use crate::serde_helpers::{option_bytes_hex, u128_hex_string};
 use crate::types::UserId;
 
@@ -535,6 +536,11 @@ pub struct EncryptedShardStore {
     /// Indices of external shards shown to user (e.g., [1, 2, 3])
     pub external_shard_indices: Vec<u8>,
 
+    /// Commitments to the external shards, for checking backups without
+    /// reconstructing the key (empty for stores written before they existed)
+    #[serde(default)]
+    pub external_shard_commitments: Vec<ShardCommitment>,
+
     /// Key derivation function parameters
     pub kdf: KeyDerivation,
 
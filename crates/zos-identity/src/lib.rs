@@ -59,9 +59,11 @@
 #![no_std]
 extern crate alloc;
 
+pub mod backup;
 pub mod client;
 pub mod crypto;
 pub mod error;
+pub mod fingerprint;
 pub mod ipc;
 pub mod keystore;
 pub mod paths;
@@ -72,6 +74,7 @@ pub mod types;
 
 // Re-export main types
 pub use error::{IdentityError, KeyError, SessionError, UserError};
+pub use fingerprint::{fingerprint_matches, key_fingerprint};
 pub use keystore::{
     EncryptedPrivateKeys, KeyDerivation, KeyScheme, LocalKeyStore, MachineKeyCapabilities,
     MachineKeyRecord,
@@ -89,7 +92,8 @@ pub use zos_process::{
 // Key management IPC types
 pub use ipc::{
     CreateMachineKeyRequest, CreateMachineKeyResponse, GetIdentityKeyRequest,
-    GetIdentityKeyResponse, GetMachineKeyRequest, GetMachineKeyResponse, ListMachineKeysRequest,
+    GetIdentityKeyResponse, GetKeyFingerprintRequest, GetKeyFingerprintResponse,
+    GetMachineKeyRequest, GetMachineKeyResponse, KeyFingerprint, ListMachineKeysRequest,
     ListMachineKeysResponse, RegisterIdentityKeyRequest, RegisterIdentityKeyResponse,
     RevokeMachineKeyRequest, RevokeMachineKeyResponse, RotateMachineKeyRequest,
     RotateMachineKeyResponse,
@@ -0,0 +1,298 @@
+//! Boot dependency graph
+//!
+//! Each boot service declares the services it needs before it can start.
+//! Init spawns a service once every dependency has signalled
+//! `MSG_SERVICE_READY`; services without dependencies are spawned right
+//! away, in declaration order. A dependency that is spawned but not ready
+//! within `DEPENDENCY_TIMEOUT_NS` is logged as an error and the dependent
+//! is spawned anyway, so one broken service degrades the boot instead of
+//! stalling it.
+
+#[cfg(target_arch = "wasm32")]
+use alloc::{format, vec::Vec};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{format, vec::Vec};
+
+use crate::Init;
+use zos_process as syscall;
+
+/// How long a dependent waits for its spawned dependencies to become ready.
+pub const DEPENDENCY_TIMEOUT_NS: u64 = 15_000_000_000;
+
+/// A service spawned at boot.
+pub struct BootService {
+    /// Binary and registry name
+    pub name: &'static str,
+    /// Services that must be ready before this one is spawned
+    pub depends_on: &'static [&'static str],
+}
+
+/// Boot services, in spawn order among those that are ready to go.
+///
+/// Every dependency must be a boot service itself and the graph must be
+/// acyclic; a service waiting on a cycle would never be spawned.
+pub const BOOT_SERVICES: &[BootService] = &[
+    // The capability authority
+    BootService {
+        name: "permission",
+        depends_on: &[],
+    },
+    BootService {
+        name: "vfs",
+        depends_on: &[],
+    },
+    // Key storage goes through the supervisor, not VFS (Invariant 32)
+    BootService {
+        name: "keystore",
+        depends_on: &[],
+    },
+    #[cfg(not(feature = "skip-identity"))]
+    BootService {
+        name: "identity",
+        depends_on: &["vfs", "keystore"],
+    },
+    // Time settings are loaded from VFS on startup
+    BootService {
+        name: "time",
+        depends_on: &["vfs"],
+    },
+    // A/B slot accounting reads slot state from VFS and must run every
+    // boot so an unconfirmed trial slot is rolled back
+    BootService {
+        name: "update",
+        depends_on: &["vfs"],
+    },
+    // Publishes the flag snapshot the supervisor consults before enabling
+    // risky subsystems
+    BootService {
+        name: "flags",
+        depends_on: &["vfs"],
+    },
+    // Screen reader output channel
+    BootService {
+        name: "speech",
+        depends_on: &[],
+    },
+    // Topic pub/sub between loosely-coupled features
+    BootService {
+        name: "events",
+        depends_on: &[],
+    },
+    // Holds requests until its policy is loaded from VFS
+    BootService {
+        name: "network",
+        depends_on: &["vfs"],
+    },
+    // Loads events from VFS and arms their reminders as TimeService alarms
+    BootService {
+        name: "calendar",
+        depends_on: &["vfs", "time"],
+    },
+    // Loads contacts from VFS; builds the user's card from IdentityService keys
+    #[cfg(not(feature = "skip-identity"))]
+    BootService {
+        name: "contacts",
+        depends_on: &["vfs", "identity"],
+    },
+    #[cfg(feature = "skip-identity")]
+    BootService {
+        name: "contacts",
+        depends_on: &["vfs"],
+    },
+    // Reads machine keys from the keystore and contacts' keys and relays from
+    // the address book; reaches relays through NetworkService
+    BootService {
+        name: "messaging",
+        depends_on: &["vfs", "keystore", "network", "contacts"],
+    },
+    // Reads its sync settings through VFS; syncs entries through messaging
+    BootService {
+        name: "clipboard",
+        depends_on: &["vfs", "messaging"],
+    },
+    // Writes rendered PDFs through VFS
+    BootService {
+        name: "print",
+        depends_on: &["vfs"],
+    },
+    // Reads images and caches thumbnails through VFS
+    BootService {
+        name: "thumbnail",
+        depends_on: &["vfs"],
+    },
+    // Streams archives and their entries through VFS file handles
+    BootService {
+        name: "archive",
+        depends_on: &["vfs"],
+    },
+    // Command palette registry; app commands arrive once apps start
+    BootService {
+        name: "commands",
+        depends_on: &[],
+    },
+];
+
+/// Spawn state of one boot service
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BootState {
+    /// Some dependency has not been spawned yet
+    Pending,
+    /// All dependencies spawned; waiting for them to be ready since this uptime
+    WaitingSince(u64),
+    /// Spawn requested
+    Spawned,
+}
+
+impl Init {
+    /// Spawn every boot service whose dependencies are ready.
+    ///
+    /// Called from the main loop until the whole graph is spawned, at which
+    /// point the boot sequence completes.
+    pub fn advance_boot(&mut self) {
+        if self.boot_complete {
+            return;
+        }
+        let now = syscall::get_time();
+
+        for (index, service) in BOOT_SERVICES.iter().enumerate() {
+            if self.boot_states[index] == BootState::Spawned {
+                continue;
+            }
+            let not_ready: Vec<&str> = service
+                .depends_on
+                .iter()
+                .copied()
+                .filter(|dep| !self.services.get(*dep).is_some_and(|info| info.ready))
+                .collect();
+
+            if not_ready.is_empty() {
+                self.spawn_boot_service(index);
+                continue;
+            }
+            if not_ready
+                .iter()
+                .any(|dep| !self.boot_dependency_spawned(dep))
+            {
+                continue;
+            }
+
+            let since = match self.boot_states[index] {
+                BootState::WaitingSince(since) => since,
+                _ => {
+                    self.log(&format!(
+                        "Boot: {} waiting for {}",
+                        service.name,
+                        not_ready.join(", ")
+                    ));
+                    self.boot_states[index] = BootState::WaitingSince(now);
+                    now
+                }
+            };
+            if now.saturating_sub(since) >= DEPENDENCY_TIMEOUT_NS {
+                self.log(&format!(
+                    "ERROR: Boot: {} not ready after {} ms, spawning {} without it",
+                    not_ready.join(", "),
+                    DEPENDENCY_TIMEOUT_NS / 1_000_000,
+                    service.name
+                ));
+                self.spawn_boot_service(index);
+            }
+        }
+
+        if self
+            .boot_states
+            .iter()
+            .all(|state| *state == BootState::Spawned)
+        {
+            self.finish_boot_sequence();
+        }
+    }
+
+    fn spawn_boot_service(&mut self, index: usize) {
+        let name = BOOT_SERVICES[index].name;
+        self.log(&format!("Spawning {}...", name));
+        self.spawn_service(name);
+        self.boot_states[index] = BootState::Spawned;
+    }
+
+    /// Whether a dependency has been spawned. Names outside the boot graph
+    /// are spawned by someone else, so only the timeout applies to them.
+    fn boot_dependency_spawned(&self, name: &str) -> bool {
+        match BOOT_SERVICES
+            .iter()
+            .position(|service| service.name == name)
+        {
+            Some(index) => self.boot_states[index] == BootState::Spawned,
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_of(name: &str) -> Option<usize> {
+        BOOT_SERVICES
+            .iter()
+            .position(|service| service.name == name)
+    }
+
+    #[test]
+    fn test_dependencies_are_boot_services() {
+        for service in BOOT_SERVICES {
+            for dep in service.depends_on {
+                assert!(
+                    index_of(dep).is_some(),
+                    "{} depends on {}, which is not in the boot graph",
+                    service.name,
+                    dep
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_boot_graph_is_acyclic() {
+        // Spawn in rounds as the boot loop does; every round must make progress
+        let mut spawned = vec![false; BOOT_SERVICES.len()];
+        while spawned.iter().any(|done| !done) {
+            let ready: Vec<usize> = (0..BOOT_SERVICES.len())
+                .filter(|&i| !spawned[i])
+                .filter(|&i| {
+                    BOOT_SERVICES[i]
+                        .depends_on
+                        .iter()
+                        .all(|dep| index_of(dep).is_some_and(|d| spawned[d]))
+                })
+                .collect();
+            let stuck: Vec<&str> = (0..BOOT_SERVICES.len())
+                .filter(|&i| !spawned[i])
+                .map(|i| BOOT_SERVICES[i].name)
+                .collect();
+            assert!(!ready.is_empty(), "dependency cycle among {:?}", stuck);
+            for i in ready {
+                spawned[i] = true;
+            }
+        }
+    }
+
+    #[test]
+    fn test_boot_services_are_unique_and_trusted() {
+        for (i, service) in BOOT_SERVICES.iter().enumerate() {
+            assert_eq!(
+                index_of(service.name),
+                Some(i),
+                "{} listed twice",
+                service.name
+            );
+            // Services act for every user: VFS and friends trust them by name
+            assert!(
+                syscall::services::SYSTEM.contains(&service.name),
+                "{} missing from zos_ipc::services::SYSTEM",
+                service.name
+            );
+        }
+    }
+}
@@ -15,47 +15,41 @@
 
 #[cfg(target_arch = "wasm32")]
 use alloc::format;
+#[cfg(target_arch = "wasm32")]
+use alloc::vec::Vec;
 #[cfg(not(target_arch = "wasm32"))]
 use std::format;
+#[cfg(not(target_arch = "wasm32"))]
+use std::vec::Vec;
 
 use crate::Init;
 use zos_process as syscall;
 use zos_process::syscall_error;
+use zos_process::update::MSG_UPDATE_HEALTH_OK;
+
+/// Services that must register before a boot counts as healthy.
+#[cfg(not(feature = "skip-identity"))]
+const BOOT_HEALTH_SERVICES: &[&str] = &["permission", "vfs", "keystore", "identity", "time"];
+#[cfg(feature = "skip-identity")]
+const BOOT_HEALTH_SERVICES: &[&str] = &["permission", "vfs", "keystore", "time"];
 
 impl Init {
-    /// Boot sequence - spawn PermissionService, VfsService, KeystoreService, IdentityService, and TimeService
+    /// Boot sequence - spawn the boot services in dependency order
+    ///
+    /// Services without dependencies are spawned here; the rest follow from
+    /// `advance_boot` in the main loop as their dependencies become ready
+    /// (see `boot_graph`).
     pub fn boot_sequence(&mut self) {
         self.log("Starting boot sequence (pure microkernel)...");
-
-        // 1. Spawn PermissionService (PID 2) - the capability authority
-        self.log("Spawning PermissionService (PID 2)...");
-        self.spawn_service("permission");
-
-        // 2. Spawn VfsService (PID 3) - virtual filesystem service
-        // NOTE: VFS must be spawned before IdentityService since identity needs VFS
-        self.log("Spawning VfsService (PID 3)...");
-        self.spawn_service("vfs");
-
-        // 3. Spawn KeystoreService (PID 4) - secure key storage
-        // NOTE: Keystore must be spawned before IdentityService since identity uses keystore
-        // for all /keys/ path operations (Invariant 32)
-        self.log("Spawning KeystoreService (PID 4)...");
-        self.spawn_service("keystore");
-
-        // 4. Spawn IdentityService (PID 5) - user identity and key management
-        #[cfg(not(feature = "skip-identity"))]
-        {
-            self.log("Spawning IdentityService (PID 5)...");
-            self.spawn_service("identity");
-        }
         #[cfg(feature = "skip-identity")]
         self.log("IdentityService skipped (QEMU mode)");
 
-        // 5. Spawn TimeService (PID 6) - time settings management
-        self.log("Spawning TimeService (PID 6)...");
-        self.spawn_service("time");
+        self.advance_boot();
+    }
 
-        // 6. Spawn Terminal (PID 7) - interactive terminal for QEMU mode only
+    /// Complete the boot once every boot service has been spawned.
+    pub(crate) fn finish_boot_sequence(&mut self) {
+        // Spawn Terminal - interactive terminal for QEMU mode only
         // In QEMU mode, we need a terminal process running to receive serial input.
         // In browser WASM mode, terminals are spawned per-window by Desktop.
         // We detect QEMU mode at runtime by checking if load_binary succeeds.
@@ -69,9 +63,66 @@ impl Init {
         #[cfg(not(feature = "skip-identity"))]
         self.log("  IdentityService: handles identity and key management");
         self.log("  TimeService: handles time settings");
+        self.log("  UpdateService: handles system updates and rollback");
+        self.log("  SpeechService: handles screen reader speech");
+        self.log("  EventBusService: handles topic publish/subscribe");
+        self.log("  CalendarService: handles calendar events and reminders");
+        self.log("  AddressBookService: handles contacts and their verified keys");
+        self.log("  MessagingService: handles end-to-end encrypted messages");
+        self.log("  ClipboardService: handles clipboard history and sync");
+        self.log("  PrintService: handles print jobs and PDF export");
+        self.log("  ThumbnailService: handles image thumbnails");
+        self.log("  ArchiveService: handles zip and tar archives");
+        self.log("  CommandService: handles command palette commands");
         self.log("Init entering minimal idle state");
     }
 
+    /// Confirm boot health to UpdateService once every boot-critical service
+    /// has registered.
+    ///
+    /// Called after each service registration. A trial boot of a newly staged
+    /// slot is only committed if this fires; otherwise UpdateService rolls the
+    /// slot back on the next boot.
+    pub fn check_boot_health(&mut self) {
+        if self.boot_health_reported {
+            return;
+        }
+        let update_pid = match self.services.get("update") {
+            Some(info) => info.pid,
+            None => return,
+        };
+        if let Some(missing) = BOOT_HEALTH_SERVICES
+            .iter()
+            .find(|name| !self.services.contains_key(**name))
+        {
+            self.log(&format!("Boot health: waiting for '{}'", missing));
+            return;
+        }
+
+        self.boot_health_reported = true;
+        self.log("Boot health: all critical services registered");
+
+        match self.service_cap_slots.get(&update_pid).copied() {
+            Some(cap_slot) => {
+                if let Err(e) = syscall::send(cap_slot, MSG_UPDATE_HEALTH_OK, &[]) {
+                    self.log(&format!("Boot health: delivery to UpdateService failed: error {}", e));
+                }
+            }
+            None => {
+                // Capability not granted yet - queue for retry_pending_deliveries
+                self.pending_deliveries
+                    .entry(update_pid)
+                    .or_default()
+                    .push(crate::PendingDelivery {
+                        target_pid: update_pid,
+                        endpoint_slot: 1,
+                        tag: MSG_UPDATE_HEALTH_OK,
+                        data: Vec::new(),
+                    });
+            }
+        }
+    }
+
     /// Try to spawn terminal for QEMU mode only.
     ///
     /// This uses runtime detection: if `load_binary("terminal")` succeeds, we're in
@@ -82,7 +133,7 @@ impl Init {
         match syscall::load_binary("terminal") {
             Ok(binary) => {
                 // QEMU mode: spawn terminal for interactive serial console
-                self.log(&format!("Spawning Terminal (PID 7) for QEMU console..."));
+                self.log("Spawning terminal for QEMU console...");
                 self.log(&format!("Loaded terminal ({} bytes)", binary.len()));
 
                 match syscall::spawn_process("terminal", &binary) {
@@ -122,7 +173,7 @@ impl Init {
     ///
     /// This method tries the pure microkernel path first (QEMU) and falls back
     /// to the Supervisor async flow (WASM) if binary loading is not supported.
-    fn spawn_service(&mut self, name: &str) {
+    pub fn spawn_service(&mut self, name: &str) {
         // Try pure microkernel approach first (works on QEMU)
         match syscall::load_binary(name) {
             Ok(binary) => {
@@ -132,6 +183,13 @@ impl Init {
                 match syscall::spawn_process(name, &binary) {
                     Ok(pid) => {
                         self.log(&format!("Spawned {} as PID {}", name, pid));
+
+                        // Services are what everything else waits on
+                        if let Err(e) =
+                            syscall::set_priority(pid, syscall::priority::SYSTEM_SERVICE)
+                        {
+                            self.log(&format!("Failed to set priority of {}: error {}", name, e));
+                        }
                         
                         // Setup endpoint and capability for the new process
                         match syscall::create_endpoint_for(pid) {
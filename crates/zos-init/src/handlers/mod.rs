@@ -1,6 +1,8 @@
 //! Message handlers for Init process
 //!
-//! Split into service and supervisor protocol handlers.
+//! Split into service, supervisor, restart and supervision handlers.
 
+pub mod restart;
 pub mod service;
+pub mod supervision;
 pub mod supervisor;
@@ -0,0 +1,167 @@
+//! Service restart handlers
+//!
+//! Restarts are drained: Init sends MSG_SERVICE_DRAIN and waits for the
+//! service to ack with MSG_SERVICE_DRAINED (pending operations finished,
+//! state checkpointed) before killing and respawning it. A service that has
+//! not drained within `DRAIN_TIMEOUT_NS` is restarted anyway.
+
+#[cfg(target_arch = "wasm32")]
+use alloc::{format, string::String, vec::Vec};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{format, string::String, vec::Vec};
+
+use crate::{DrainingService, Init, MSG_SERVICE_DRAIN};
+use zos_process as syscall;
+
+/// How long a service may take to drain before it is restarted anyway.
+pub const DRAIN_TIMEOUT_NS: u64 = 10_000_000_000;
+
+impl Init {
+    /// Handle supervisor request to restart a service.
+    ///
+    /// Payload: [service_pid: u32]
+    pub fn handle_supervisor_restart_service(&mut self, msg: &syscall::ReceivedMessage) {
+        // Verify sender is supervisor (PID 0)
+        if msg.from_pid != 0 {
+            self.log(&format!(
+                "SECURITY: Restart request from non-supervisor PID {}",
+                msg.from_pid
+            ));
+            return;
+        }
+
+        if msg.data.len() < 4 {
+            self.log("SupervisorRestartService: message too short");
+            return;
+        }
+        let service_pid = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
+
+        let name = match self.service_name(service_pid) {
+            Some(name) => name,
+            None => {
+                self.log(&format!(
+                    "Restart: PID {} is not a registered service",
+                    service_pid
+                ));
+                return;
+            }
+        };
+        if self.draining.contains_key(&service_pid) {
+            self.log(&format!("Restart: {} is already draining", name));
+            return;
+        }
+
+        let cap_slot = match self.service_cap_slots.get(&service_pid) {
+            Some(&slot) => slot,
+            None => {
+                self.log(&format!(
+                    "Restart: no capability for {} (PID {}), restarting without drain",
+                    name, service_pid
+                ));
+                self.restart_service(service_pid, &name);
+                return;
+            }
+        };
+
+        match syscall::send(cap_slot, MSG_SERVICE_DRAIN, &[]) {
+            Ok(()) => {
+                self.log(&format!(
+                    "Draining {} (PID {}) for restart",
+                    name, service_pid
+                ));
+                self.draining.insert(
+                    service_pid,
+                    DrainingService {
+                        name,
+                        deadline_ns: syscall::get_time().saturating_add(DRAIN_TIMEOUT_NS),
+                    },
+                );
+            }
+            Err(e) => {
+                self.log(&format!(
+                    "Restart: drain request to {} failed (error {}), restarting without drain",
+                    name, e
+                ));
+                self.restart_service(service_pid, &name);
+            }
+        }
+    }
+
+    /// Handle a service's ack that it has drained.
+    pub fn handle_service_drained(&mut self, msg: &syscall::ReceivedMessage) {
+        match self.draining.remove(&msg.from_pid) {
+            Some(service) => {
+                self.log(&format!("{} (PID {}) drained", service.name, msg.from_pid));
+                self.restart_service(msg.from_pid, &service.name);
+            }
+            None => self.log(&format!("Unexpected drain ack from PID {}", msg.from_pid)),
+        }
+    }
+
+    /// Restart services that did not drain in time.
+    pub fn check_drain_deadlines(&mut self) {
+        if self.draining.is_empty() {
+            return;
+        }
+        let now = syscall::get_time();
+        let expired: Vec<u32> = self
+            .draining
+            .iter()
+            .filter(|(_, service)| now >= service.deadline_ns)
+            .map(|(&pid, _)| pid)
+            .collect();
+        for pid in expired {
+            if let Some(service) = self.draining.remove(&pid) {
+                self.log(&format!(
+                    "{} (PID {}) did not drain in time, restarting anyway",
+                    service.name, pid
+                ));
+                self.restart_service(pid, &service.name);
+            }
+        }
+    }
+
+    /// Name a PID is registered under.
+    pub(crate) fn service_name(&self, pid: u32) -> Option<String> {
+        self.services
+            .iter()
+            .find(|(_, info)| info.pid == pid)
+            .map(|(name, _)| name.clone())
+    }
+
+    /// Kill a service, forget its registration and spawn it again.
+    ///
+    /// The new instance registers itself under the same name.
+    fn restart_service(&mut self, pid: u32, name: &str) {
+        self.check_lifecycle("restart", Some(pid));
+        match syscall::kill(pid) {
+            Ok(()) => syscall::debug(&format!("INIT:KILL_OK:{}", pid)),
+            Err(e) => {
+                self.log(&format!(
+                    "Restart: failed to kill {} (PID {}): error {}",
+                    name, pid, e
+                ));
+                syscall::debug(&format!("INIT:KILL_FAIL:{}:{}", pid, e));
+                return;
+            }
+        }
+
+        self.services.remove(name);
+        self.forget_process(pid);
+
+        self.log(&format!("Restarting {} (was PID {})", name, pid));
+        self.spawn_service(name);
+    }
+
+    /// Drop everything Init holds for a process that is gone.
+    pub(crate) fn forget_process(&mut self, pid: u32) {
+        self.service_cap_slots.remove(&pid);
+        self.service_vfs_slots.remove(&pid);
+        self.pending_deliveries.remove(&pid);
+        self.draining.remove(&pid);
+        self.process_names.remove(&pid);
+        self.stopping.remove(&pid);
+        self.forget_lookup_reply(pid);
+    }
+}
@@ -39,8 +39,8 @@ impl Init {
             name, msg.from_pid
         ));
 
-        // Request supervisor to spawn
-        syscall::debug(&format!("INIT:SPAWN:{}", name));
+        // Request supervisor to spawn into the requester's process group
+        syscall::debug(&format!("INIT:SPAWN:{}:{}", name, msg.from_pid));
     }
 
     /// Handle service capability pre-registration from supervisor.
@@ -169,4 +169,36 @@ impl Init {
 
         self.service_vfs_slots.insert(service_pid, cap_slot);
     }
+
+    /// Handle lookup-reply endpoint notification from supervisor.
+    ///
+    /// Every process gets a lookup-reply endpoint at spawn. Init owns it
+    /// (the process can only receive from it), so the slot here is Init's
+    /// own full capability, used to answer the process's lookups.
+    ///
+    /// Payload: [process_pid: u32, cap_slot: u32]
+    pub fn handle_lookup_reply_cap_granted(&mut self, msg: &syscall::ReceivedMessage) {
+        // Verify sender is supervisor (PID 0)
+        if msg.from_pid != 0 {
+            self.log(&format!(
+                "SECURITY: Lookup reply cap notification from non-supervisor PID {}",
+                msg.from_pid
+            ));
+            return;
+        }
+
+        // Parse: [process_pid: u32, cap_slot: u32]
+        if msg.data.len() < 8 {
+            self.log("LookupReplyCapGranted: message too short");
+            return;
+        }
+
+        let process_pid = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
+        let cap_slot = u32::from_le_bytes([msg.data[4], msg.data[5], msg.data[6], msg.data[7]]);
+
+        // A reused PID replaces the stale endpoint
+        if let Some(old_slot) = self.lookup_reply_slots.insert(process_pid, cap_slot) {
+            let _ = syscall::cap_delete(old_slot);
+        }
+    }
 }
@@ -0,0 +1,528 @@
+//! Service supervision
+//!
+//! The kernel tells Init about every process exit with MSG_PROCESS_EXITED.
+//! When the process was a registered service, Init forgets its registration
+//! and, depending on the service's restart policy, spawns it again after an
+//! exponential backoff. A service that stays up for `STABLE_UPTIME_NS` has
+//! its backoff reset, so only crash loops are slowed down.
+//!
+//! # Crash loops
+//!
+//! A non-zero exit that the supervisor did not ask for is a crash, for
+//! services and apps alike. Each one is reported to the supervisor as
+//! `INIT:CRASH:`. `CRASH_LOOP_LIMIT` crashes within `CRASH_LOOP_WINDOW_NS`
+//! make a crash loop: the name is marked failed and a service is no longer
+//! restarted, until the supervisor resets it with
+//! MSG_SUPERVISOR_RESET_CRASHES. Apps are only relaunched by the desktop,
+//! which stops doing so for a failed app.
+//!
+//! # Memory limits
+//!
+//! A process that grows past its memory limit is terminated by the kernel,
+//! which sends MSG_PROCESS_OOM just before the exit. Init logs it; the exit
+//! itself is a crash like any other and goes through the same restart and
+//! crash loop handling.
+
+#[cfg(target_arch = "wasm32")]
+use alloc::{format, string::String, vec::Vec};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{format, string::String, vec::Vec};
+
+use crate::{Init, SupervisedService};
+use zos_process as syscall;
+
+/// Delay before the first restart of a crashed service.
+pub const RESTART_BACKOFF_BASE_NS: u64 = 500_000_000;
+
+/// Upper bound for the restart delay.
+pub const RESTART_BACKOFF_MAX_NS: u64 = 30_000_000_000;
+
+/// Uptime after which a restarted service counts as healthy again.
+pub const STABLE_UPTIME_NS: u64 = 60_000_000_000;
+
+/// Crashes within `CRASH_LOOP_WINDOW_NS` that make a crash loop.
+pub const CRASH_LOOP_LIMIT: usize = 5;
+
+/// Window over which crashes are counted.
+pub const CRASH_LOOP_WINDOW_NS: u64 = 60_000_000_000;
+
+/// When a service is restarted after it exits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Restart whenever the service exits
+    Always,
+    /// Restart only if the service exited with a non-zero code, was killed
+    /// or faulted
+    OnFailure,
+    /// Never restart
+    Never,
+}
+
+/// Restart policy per service; services not listed use `OnFailure`.
+const RESTART_POLICIES: &[(&str, RestartPolicy)] = &[
+    ("permission", RestartPolicy::Always),
+    ("vfs", RestartPolicy::Always),
+    ("keystore", RestartPolicy::Always),
+    ("identity", RestartPolicy::Always),
+    ("time", RestartPolicy::Always),
+    // A/B slot accounting runs once per boot; a second instance would count
+    // the boot twice
+    ("update", RestartPolicy::Never),
+];
+
+impl RestartPolicy {
+    /// Whether a service exiting with `code` is restarted.
+    pub fn restarts(self, code: i32) -> bool {
+        match self {
+            Self::Always => true,
+            Self::OnFailure => code != 0,
+            Self::Never => false,
+        }
+    }
+}
+
+/// Restart policy for a service name.
+pub fn restart_policy(name: &str) -> RestartPolicy {
+    RESTART_POLICIES
+        .iter()
+        .find(|(service, _)| *service == name)
+        .map(|(_, policy)| *policy)
+        .unwrap_or(RestartPolicy::OnFailure)
+}
+
+/// Delay before restart number `attempt` (0-based).
+fn backoff_ns(attempt: u32) -> u64 {
+    RESTART_BACKOFF_BASE_NS
+        .checked_shl(attempt)
+        .unwrap_or(u64::MAX)
+        .min(RESTART_BACKOFF_MAX_NS)
+}
+
+impl SupervisedService {
+    /// Schedule the next restart, returning its delay. A service that was
+    /// up for `STABLE_UPTIME_NS` starts over from the shortest delay.
+    fn schedule_restart(&mut self, now: u64) -> u64 {
+        if now.saturating_sub(self.started_ns) >= STABLE_UPTIME_NS {
+            self.attempts = 0;
+        }
+        let delay = backoff_ns(self.attempts);
+        self.attempts = self.attempts.saturating_add(1);
+        self.restart_at_ns = Some(now.saturating_add(delay));
+        delay
+    }
+
+    /// Count a crash. Returns the crashes within `CRASH_LOOP_WINDOW_NS`
+    /// and whether this one made a crash loop.
+    fn count_crash(&mut self, now: u64) -> (usize, bool) {
+        self.crashes
+            .retain(|&at| now.saturating_sub(at) < CRASH_LOOP_WINDOW_NS);
+        self.crashes.push(now);
+        let newly_failed = !self.failed && self.crashes.len() >= CRASH_LOOP_LIMIT;
+        if newly_failed {
+            self.failed = true;
+            self.restart_at_ns = None;
+        }
+        (self.crashes.len(), newly_failed)
+    }
+}
+
+impl Init {
+    /// Handle the kernel's notification that a process exited.
+    ///
+    /// Payload: [pid: u32, exit_code: i32]
+    pub fn handle_process_exited(&mut self, msg: &syscall::ReceivedMessage) {
+        // Only the kernel reports exits
+        if msg.from_pid != 0 {
+            self.log(&format!(
+                "SECURITY: Exit notification from non-kernel PID {}",
+                msg.from_pid
+            ));
+            return;
+        }
+
+        if msg.data.len() < 8 {
+            self.log("ProcessExited: message too short");
+            return;
+        }
+        let pid = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
+        let code = i32::from_le_bytes([msg.data[4], msg.data[5], msg.data[6], msg.data[7]]);
+
+        // Services restarted on request were already forgotten
+        let stopped = self.stopping.contains(&pid);
+        let service = self.service_name(pid);
+        let name = service
+            .clone()
+            .or_else(|| self.process_names.get(&pid).cloned());
+        self.forget_process(pid);
+        let name = match name {
+            Some(name) => name,
+            None => return,
+        };
+        let crashed = code != 0 && !stopped;
+        let failed = crashed && self.record_crash(&name, pid, code, service.is_some());
+        if service.is_none() {
+            return;
+        }
+        self.services.remove(&name);
+        if failed {
+            return;
+        }
+
+        let policy = restart_policy(&name);
+        if !policy.restarts(code) {
+            self.log(&format!(
+                "{} (PID {}) exited with code {}, not restarting ({:?})",
+                name, pid, code, policy
+            ));
+            self.supervised.remove(&name);
+            return;
+        }
+
+        let now = syscall::get_time();
+        let service = self.supervised.entry(name.clone()).or_default();
+        let delay = service.schedule_restart(now);
+        let attempt = service.attempts;
+
+        self.log(&format!(
+            "{} (PID {}) exited with code {}, restart {} in {} ms",
+            name,
+            pid,
+            code,
+            attempt,
+            delay / 1_000_000
+        ));
+    }
+
+    /// Handle the kernel's notification that a process ran out of memory.
+    ///
+    /// Payload: [pid: u32, memory_size: u64, limit: u64]
+    pub fn handle_process_oom(&mut self, msg: &syscall::ReceivedMessage) {
+        if msg.from_pid != 0 {
+            self.log(&format!(
+                "SECURITY: OOM notification from non-kernel PID {}",
+                msg.from_pid
+            ));
+            return;
+        }
+
+        if msg.data.len() < 20 {
+            self.log("ProcessOom: message too short");
+            return;
+        }
+        let pid = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
+        let mut size = [0u8; 8];
+        size.copy_from_slice(&msg.data[4..12]);
+        let mut limit = [0u8; 8];
+        limit.copy_from_slice(&msg.data[12..20]);
+
+        let name = self
+            .service_name(pid)
+            .or_else(|| self.process_names.get(&pid).cloned())
+            .unwrap_or_else(|| String::from("unknown"));
+        self.log(&format!(
+            "{} (PID {}) out of memory: {} KiB over a limit of {} KiB, terminated",
+            name,
+            pid,
+            u64::from_le_bytes(size) / 1024,
+            u64::from_le_bytes(limit) / 1024
+        ));
+    }
+
+    /// Record a crash of `name` and report it to the supervisor.
+    ///
+    /// Returns whether `name` is in a crash loop.
+    fn record_crash(&mut self, name: &str, pid: u32, code: i32, service: bool) -> bool {
+        let now = syscall::get_time();
+        let entry = self.supervised.entry(String::from(name)).or_default();
+        entry.service = service;
+        let (crashes, newly_failed) = entry.count_crash(now);
+        let failed = entry.failed;
+
+        syscall::debug(&format!(
+            "INIT:CRASH:{}:{}:{}:{}:{}",
+            pid, code, crashes, failed as u8, name
+        ));
+        if newly_failed {
+            self.log(&format!(
+                "{} crashed {} times within {} s, not restarting",
+                name,
+                crashes,
+                CRASH_LOOP_WINDOW_NS / 1_000_000_000
+            ));
+        }
+        failed
+    }
+
+    /// Handle supervisor request to forget a name's crashes.
+    ///
+    /// A service stopped by a crash loop is restarted right away.
+    ///
+    /// Payload: [name: [u8]]
+    pub fn handle_supervisor_reset_crashes(&mut self, msg: &syscall::ReceivedMessage) {
+        if msg.from_pid != 0 {
+            self.log(&format!(
+                "SECURITY: Crash reset request from non-supervisor PID {}",
+                msg.from_pid
+            ));
+            return;
+        }
+        let name = match core::str::from_utf8(&msg.data) {
+            Ok(name) => name,
+            Err(_) => {
+                self.log("SupervisorResetCrashes: invalid UTF-8 in name");
+                return;
+            }
+        };
+        let now = syscall::get_time();
+        let Some(entry) = self.supervised.get_mut(name) else {
+            return;
+        };
+        let restart = entry.failed && entry.service;
+        entry.crashes.clear();
+        entry.failed = false;
+        entry.attempts = 0;
+        if restart {
+            entry.restart_at_ns = Some(now);
+        }
+        self.log(&format!("Crashes of {} reset", name));
+    }
+
+    /// Respawn services whose restart backoff has elapsed.
+    pub fn check_restart_schedule(&mut self) {
+        let now = syscall::get_time();
+        let due: Vec<String> = self
+            .supervised
+            .iter()
+            .filter(|(_, service)| service.restart_at_ns.is_some_and(|at| now >= at))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in due {
+            if let Some(service) = self.supervised.get_mut(&name) {
+                service.restart_at_ns = None;
+                service.started_ns = now;
+            }
+            self.log(&format!("Restarting crashed service {}", name));
+            self.spawn_service(&name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ServiceInfo, MSG_PROCESS_EXITED, MSG_SUPERVISOR_RESET_CRASHES};
+
+    fn exited(pid: u32, code: i32) -> syscall::ReceivedMessage {
+        let mut data = pid.to_le_bytes().to_vec();
+        data.extend_from_slice(&code.to_le_bytes());
+        syscall::ReceivedMessage {
+            from_pid: 0,
+            tag: MSG_PROCESS_EXITED,
+            cap_slots: Vec::new(),
+            correlation_id: None,
+            data,
+        }
+    }
+
+    fn with_service(name: &str, pid: u32) -> Init {
+        let mut init = Init::new();
+        init.services.insert(
+            String::from(name),
+            ServiceInfo {
+                pid,
+                endpoint_id: 0,
+                ready: true,
+            },
+        );
+        init
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        assert_eq!(backoff_ns(0), RESTART_BACKOFF_BASE_NS);
+        assert_eq!(backoff_ns(1), 2 * RESTART_BACKOFF_BASE_NS);
+        assert_eq!(backoff_ns(3), 8 * RESTART_BACKOFF_BASE_NS);
+        assert_eq!(backoff_ns(6), RESTART_BACKOFF_MAX_NS);
+        // Shifts past the width of u64 stay capped
+        assert_eq!(backoff_ns(64), RESTART_BACKOFF_MAX_NS);
+        assert_eq!(backoff_ns(u32::MAX), RESTART_BACKOFF_MAX_NS);
+    }
+
+    #[test]
+    fn test_backoff_resets_after_stable_uptime() {
+        let mut service = SupervisedService::default();
+        assert_eq!(service.schedule_restart(0), RESTART_BACKOFF_BASE_NS);
+        assert_eq!(service.schedule_restart(1), 2 * RESTART_BACKOFF_BASE_NS);
+        assert_eq!(service.restart_at_ns, Some(1 + 2 * RESTART_BACKOFF_BASE_NS));
+
+        // Restarted, then up just short of stable: the backoff keeps growing
+        service.started_ns = 10;
+        let now = 10 + STABLE_UPTIME_NS - 1;
+        assert_eq!(service.schedule_restart(now), 4 * RESTART_BACKOFF_BASE_NS);
+
+        // Up for STABLE_UPTIME_NS: back to the shortest delay
+        let now = 10 + STABLE_UPTIME_NS;
+        assert_eq!(service.schedule_restart(now), RESTART_BACKOFF_BASE_NS);
+        assert_eq!(service.attempts, 1);
+    }
+
+    #[test]
+    fn test_restart_policies() {
+        assert_eq!(restart_policy("vfs"), RestartPolicy::Always);
+        assert_eq!(restart_policy("update"), RestartPolicy::Never);
+        assert_eq!(restart_policy("calendar"), RestartPolicy::OnFailure);
+
+        assert!(RestartPolicy::Always.restarts(0));
+        assert!(RestartPolicy::OnFailure.restarts(-1));
+        assert!(!RestartPolicy::OnFailure.restarts(0));
+        assert!(!RestartPolicy::Never.restarts(1));
+    }
+
+    #[test]
+    fn test_never_policy_is_not_restarted() {
+        let mut init = with_service("update", 12);
+        init.supervised
+            .insert(String::from("update"), SupervisedService::default());
+        init.handle_message(&exited(12, 1));
+
+        assert!(!init.services.contains_key("update"));
+        assert!(!init.supervised.contains_key("update"));
+    }
+
+    #[test]
+    fn test_on_failure_policy_restarts_failures_only() {
+        // A clean exit is left alone
+        let mut init = with_service("calendar", 20);
+        init.handle_message(&exited(20, 0));
+        assert!(!init.services.contains_key("calendar"));
+        assert!(!init.supervised.contains_key("calendar"));
+
+        // A failure is restarted after the backoff
+        let mut init = with_service("calendar", 21);
+        init.handle_message(&exited(21, 3));
+        let service = &init.supervised["calendar"];
+        assert_eq!(service.attempts, 1);
+        assert_eq!(service.restart_at_ns, Some(RESTART_BACKOFF_BASE_NS));
+    }
+
+    #[test]
+    fn test_crash_loop_threshold() {
+        let mut service = SupervisedService::default();
+        for n in 1..CRASH_LOOP_LIMIT {
+            assert_eq!(service.count_crash(n as u64), (n, false));
+        }
+        assert!(!service.failed);
+
+        service.restart_at_ns = Some(100);
+        assert_eq!(
+            service.count_crash(CRASH_LOOP_LIMIT as u64),
+            (CRASH_LOOP_LIMIT, true)
+        );
+        assert!(service.failed);
+        assert_eq!(service.restart_at_ns, None);
+
+        // Already failed: counted, but not a new crash loop
+        assert_eq!(service.count_crash(100), (CRASH_LOOP_LIMIT + 1, false));
+    }
+
+    #[test]
+    fn test_crash_loop_window() {
+        let mut service = SupervisedService::default();
+        let spacing = CRASH_LOOP_WINDOW_NS / (CRASH_LOOP_LIMIT as u64 - 1);
+
+        // Crashes spread over more than the window never make a loop
+        for n in 0..3 * CRASH_LOOP_LIMIT as u64 {
+            let (crashes, failed) = service.count_crash(n * spacing);
+            assert!(crashes < CRASH_LOOP_LIMIT);
+            assert!(!failed);
+        }
+
+        // A crash exactly one window later no longer counts the first
+        let mut service = SupervisedService::default();
+        service.count_crash(0);
+        assert_eq!(service.count_crash(CRASH_LOOP_WINDOW_NS - 1), (2, false));
+        assert_eq!(service.count_crash(CRASH_LOOP_WINDOW_NS), (2, false));
+        assert_eq!(
+            service.crashes,
+            [CRASH_LOOP_WINDOW_NS - 1, CRASH_LOOP_WINDOW_NS]
+        );
+    }
+
+    #[test]
+    fn test_crash_loop_stops_restarts() {
+        let mut init = Init::new();
+        for pid in 20..20 + CRASH_LOOP_LIMIT as u32 {
+            init.services.insert(
+                String::from("calendar"),
+                ServiceInfo {
+                    pid,
+                    endpoint_id: 0,
+                    ready: true,
+                },
+            );
+            init.handle_message(&exited(pid, 1));
+        }
+        let service = &init.supervised["calendar"];
+        assert!(service.failed);
+        assert!(service.service);
+        assert_eq!(service.restart_at_ns, None);
+
+        // Asked to stop: not a crash
+        let mut init = with_service("calendar", 30);
+        init.stopping.insert(30);
+        init.handle_message(&exited(30, 1));
+        assert!(init.supervised["calendar"].crashes.is_empty());
+    }
+
+    #[test]
+    fn test_reset_crashes() {
+        let reset = |name: &str, from_pid: u32| syscall::ReceivedMessage {
+            from_pid,
+            tag: MSG_SUPERVISOR_RESET_CRASHES,
+            cap_slots: Vec::new(),
+            correlation_id: None,
+            data: name.as_bytes().to_vec(),
+        };
+        let failed = |service: bool| SupervisedService {
+            attempts: 4,
+            crashes: vec![1, 2, 3, 4, 5],
+            failed: true,
+            service,
+            ..Default::default()
+        };
+        let mut init = Init::new();
+        init.supervised
+            .insert(String::from("calendar"), failed(true));
+        init.supervised.insert(String::from("notes"), failed(false));
+
+        // Only the supervisor may reset
+        init.handle_message(&reset("calendar", 7));
+        assert!(init.supervised["calendar"].failed);
+
+        // A service is restarted right away
+        init.handle_message(&reset("calendar", 0));
+        let service = &init.supervised["calendar"];
+        assert!(!service.failed);
+        assert!(service.crashes.is_empty());
+        assert_eq!(service.attempts, 0);
+        assert_eq!(service.restart_at_ns, Some(0));
+
+        // An app is left for the desktop to relaunch
+        init.handle_message(&reset("notes", 0));
+        let app = &init.supervised["notes"];
+        assert!(!app.failed);
+        assert!(app.crashes.is_empty());
+        assert_eq!(app.restart_at_ns, None);
+    }
+
+    #[test]
+    fn test_exit_reports_only_from_kernel() {
+        let mut init = with_service("calendar", 20);
+        let mut msg = exited(20, 3);
+        msg.from_pid = 20;
+        init.handle_message(&msg);
+        assert!(init.services.contains_key("calendar"));
+        assert!(init.supervised.is_empty());
+    }
+}
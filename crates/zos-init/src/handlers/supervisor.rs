@@ -106,25 +106,35 @@ impl Init {
             return;
         }
 
-        // Parse: [target_pid: u32]
+        // Parse: [target_pid: u32, crashed: u8 (optional)]
         if msg.data.len() < 4 {
             self.log("SupervisorKillProcess: message too short");
             return;
         }
 
         let target_pid = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
+        let crashed = msg.data.get(4) == Some(&1);
 
         self.log(&format!("Supervisor requested kill of PID {}", target_pid));
+        // An exit already reported left no name behind to track
+        let known = self.process_names.contains_key(&target_pid)
+            || self.service_name(target_pid).is_some();
+        if !crashed && known {
+            self.stopping.insert(target_pid);
+        }
 
         // Invoke the kill syscall
         // Init (PID 1) has implicit permission to kill any process
+        self.check_lifecycle("kill", Some(target_pid));
         match syscall::kill(target_pid) {
             Ok(()) => {
                 self.log(&format!("Process {} terminated successfully", target_pid));
+                self.forget_lookup_reply(target_pid);
                 // Notify supervisor of success
                 syscall::debug(&format!("INIT:KILL_OK:{}", target_pid));
             }
             Err(e) => {
+                self.stopping.remove(&target_pid);
                 self.log(&format!(
                     "Failed to kill process {}: error {}",
                     target_pid, e
@@ -249,7 +259,7 @@ impl Init {
 
             self.pending_deliveries
                 .entry(target_pid)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(pending);
 
             // Notify supervisor to re-grant the capability
@@ -356,9 +366,11 @@ impl Init {
 
         // Register the process via SYS_REGISTER_PROCESS syscall
         // This syscall is Init-only and logs to SysLog
+        self.check_lifecycle("register", None);
         match syscall::register_process(name) {
             Ok(pid) => {
                 self.log(&format!("Process '{}' registered with PID {}", name, pid));
+                self.process_names.insert(pid, String::from(name));
                 self.send_spawn_response(1, pid); // success
             }
             Err(e) => {
@@ -414,6 +426,7 @@ impl Init {
 
         // Create endpoint via SYS_CREATE_ENDPOINT_FOR syscall
         // This syscall is Init-only and logs to SysLog
+        self.check_lifecycle("create endpoint", Some(target_pid));
         match syscall::create_endpoint_for(target_pid) {
             Ok((endpoint_id, slot)) => {
                 self.log(&format!(
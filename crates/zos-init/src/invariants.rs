@@ -0,0 +1,71 @@
+//! Runtime invariant checks (`debug-invariants` feature)
+//!
+//! All lifecycle ops flow through Init, on behalf of the supervisor or of
+//! Init's own boot and supervision work. With the feature enabled, Init
+//! checks before each lifecycle op it performs for a message (register,
+//! endpoint creation, kill, restart) that it is not acting for some other
+//! process: the message must come from the supervisor or from the process
+//! the op targets (a service acking its own drain). Boot spawns and
+//! scheduled restarts are Init's own and are not tied to a message.
+//!
+//! A violation is reported to the console and the supervisor, then panics.
+
+#[cfg(all(feature = "debug-invariants", target_arch = "wasm32"))]
+use alloc::format;
+
+#[cfg(all(feature = "debug-invariants", not(target_arch = "wasm32")))]
+use std::format;
+
+#[cfg(feature = "debug-invariants")]
+use core::fmt;
+
+use crate::Init;
+#[cfg(feature = "debug-invariants")]
+use zos_process as syscall;
+
+/// A lifecycle op Init was about to perform for the wrong process.
+#[cfg(feature = "debug-invariants")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvariantViolation {
+    /// The lifecycle op (`"kill"`, `"spawn"`, ...)
+    pub op: &'static str,
+    /// The process it targets, if known yet
+    pub target: Option<u32>,
+    /// Sender of the message being handled
+    pub origin: u32,
+}
+
+#[cfg(feature = "debug-invariants")]
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invariant lifecycle-through-init violated: {}", self.op)?;
+        if let Some(target) = self.target {
+            write!(f, " of PID {}", target)?;
+        }
+        write!(f, " while handling a message from PID {}", self.origin)
+    }
+}
+
+#[cfg(feature = "debug-invariants")]
+impl Init {
+    /// Check that lifecycle op `op` on `target` is Init's own or the
+    /// supervisor's.
+    pub(crate) fn check_lifecycle(&self, op: &'static str, target: Option<u32>) {
+        let Some(origin) = self.handling else {
+            return;
+        };
+        if origin == syscall::pid::SUPERVISOR || target == Some(origin) {
+            return;
+        }
+        let violation = InvariantViolation { op, target, origin };
+        self.log(&format!("INVARIANT VIOLATION: {}", violation));
+        syscall::debug(&format!("INIT:INVARIANT:{}", violation));
+        panic!("{}", violation);
+    }
+}
+
+#[cfg(not(feature = "debug-invariants"))]
+impl Init {
+    /// Lifecycle checks are compiled out without `debug-invariants`.
+    pub(crate) fn check_lifecycle(&self, _op: &'static str, _target: Option<u32>) {}
+}
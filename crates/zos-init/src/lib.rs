@@ -17,33 +17,47 @@
 //! - `MSG_LOOKUP_SERVICE (0x1001)`: Look up a service by name
 //! - `MSG_LOOKUP_RESPONSE (0x1002)`: Response to a lookup request
 //! - `MSG_SPAWN_SERVICE (0x1003)`: Request init to spawn a new service
+//! - `MSG_SERVICE_READY (0x1005)`: A registered service is ready; boot services
+//!   that depend on it are spawned next (see `boot_graph`)
+//! - `MSG_SERVICE_DRAINED (0x100A)`: A service finished draining before restart
+//! - `MSG_PROCESS_EXITED (0x100C)`: The kernel reports a process exit; crashed
+//!   services are restarted per their restart policy, until a crash loop
+//!   stops them (see `handlers::supervision`)
+//! - `MSG_PROCESS_OOM (0x100D)`: The kernel terminated a process that grew
+//!   past its memory limit; logged, its exit follows as a crash
+//!
+//! Lookup responses go to the requester's lookup-reply endpoint, which the
+//! supervisor creates for every process at spawn and announces with
+//! `MSG_LOOKUP_REPLY_CAP_GRANTED (0x100B)`.
 
 #![cfg_attr(target_arch = "wasm32", no_std)]
 
-// Initialize bump allocator with 6MB heap
-// Must match the WASM initial-memory linker setting to avoid OOB errors.
+// Initialize bump allocator with 7MB heap
+// Must fit under the WASM max-memory linker setting (.cargo/config.toml);
+// the allocator grows memory into it as the heap fills.
 // Bump allocator never frees, so we need space for ALL binaries loaded during boot:
 // - permission: ~282KB load + ~282KB spawn payload = 564KB
 // - vfs: ~462KB load + ~462KB spawn payload = 924KB
 // - keystore: ~369KB load + ~369KB spawn payload = 738KB
 // - identity: ~1.17MB load + ~1.17MB spawn payload = 2.35MB (largest!)
 // - time: ~386KB load + ~386KB spawn payload = 772KB
+// - update: ~420KB load + ~420KB spawn payload = 840KB
 // - format strings and overhead: ~200KB
-// Total: ~5.5MB, bump allocator never frees so we need all this space
-zos_allocator::init!(6 * 1024 * 1024);
+// Total: ~6.3MB, bump allocator never frees so we need all this space
+zos_allocator::init!(7 * 1024 * 1024);
 
 #[cfg(target_arch = "wasm32")]
 extern crate alloc;
 
 #[cfg(target_arch = "wasm32")]
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 #[cfg(target_arch = "wasm32")]
 use alloc::format;
 #[cfg(target_arch = "wasm32")]
 use alloc::string::String;
 
 #[cfg(not(target_arch = "wasm32"))]
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 #[cfg(not(target_arch = "wasm32"))]
 use std::format;
 #[cfg(not(target_arch = "wasm32"))]
@@ -55,8 +69,10 @@ use zos_process::{self as syscall};
 // Module Organization
 // =============================================================================
 
+mod boot_graph;
 mod bootstrap;
 mod handlers;
+mod invariants;
 mod registry;
 
 // =============================================================================
@@ -71,12 +87,17 @@ pub use zos_process::{
 };
 
 // Additional Init-specific constants from zos-ipc
-pub use zos_process::init::{MSG_SERVICE_CAP_GRANTED, MSG_SERVICE_CAP_PREREGISTER, MSG_VFS_RESPONSE_CAP_GRANTED};
+pub use zos_process::init::{
+    MSG_LOOKUP_REPLY_CAP_GRANTED, MSG_PROCESS_EXITED, MSG_PROCESS_OOM, MSG_SERVICE_CAP_GRANTED,
+    MSG_SERVICE_CAP_PREREGISTER, MSG_SERVICE_DRAIN, MSG_SERVICE_DRAINED,
+    MSG_VFS_RESPONSE_CAP_GRANTED,
+};
 
 // Spawn protocol messages for Init-driven spawn
 pub use zos_process::supervisor::{
     MSG_SUPERVISOR_CAP_RESPONSE, MSG_SUPERVISOR_CREATE_ENDPOINT, MSG_SUPERVISOR_ENDPOINT_RESPONSE,
-    MSG_SUPERVISOR_GRANT_CAP, MSG_SUPERVISOR_SPAWN_PROCESS, MSG_SUPERVISOR_SPAWN_RESPONSE,
+    MSG_SUPERVISOR_GRANT_CAP, MSG_SUPERVISOR_RESET_CRASHES, MSG_SUPERVISOR_RESTART_SERVICE,
+    MSG_SUPERVISOR_SPAWN_PROCESS, MSG_SUPERVISOR_SPAWN_RESPONSE,
 };
 
 // =============================================================================
@@ -123,6 +144,32 @@ pub struct PendingDelivery {
     pub data: Vec<u8>,
 }
 
+/// A service asked to drain before being restarted
+#[derive(Clone, Debug)]
+pub struct DrainingService {
+    /// Registered service name (used to respawn it)
+    pub name: String,
+    /// Uptime after which the service is restarted without waiting
+    pub deadline_ns: u64,
+}
+
+/// Restart bookkeeping for a service or app that has exited at least once
+#[derive(Clone, Debug, Default)]
+pub struct SupervisedService {
+    /// Restarts since the service was last stable
+    pub attempts: u32,
+    /// Uptime at which the current instance was spawned
+    pub started_ns: u64,
+    /// Uptime at which the pending restart is due
+    pub restart_at_ns: Option<u64>,
+    /// Uptimes of the crashes within the crash-loop window, oldest first
+    pub crashes: Vec<u64>,
+    /// Stopped by a crash loop: not restarted until its crashes are reset
+    pub failed: bool,
+    /// Registered as a service; apps are relaunched by the desktop, not Init
+    pub service: bool,
+}
+
 /// Init process state
 pub struct Init {
     /// Service registry: name → info
@@ -133,13 +180,31 @@ pub struct Init {
     /// Service VFS response capability slots: service_pid → capability slot in Init's CSpace
     /// Used for delivering VFS responses to services' VFS response endpoint (slot 4)
     pub service_vfs_slots: BTreeMap<u32, u32>,
+    /// Lookup-reply capability slots: process_pid → capability slot in Init's CSpace
+    /// Used for answering MSG_LOOKUP_SERVICE over IPC
+    pub lookup_reply_slots: BTreeMap<u32, u32>,
     /// Pending IPC deliveries waiting for capability grants.
     /// Keyed by target PID for quick lookup when capability arrives.
     pub pending_deliveries: BTreeMap<u32, Vec<PendingDelivery>>,
     /// Our endpoint slot for receiving messages
     pub endpoint_slot: u32,
+    /// Spawn state of each boot service, indexed like `boot_graph::BOOT_SERVICES`
+    pub boot_states: Vec<boot_graph::BootState>,
     /// Boot sequence complete
     pub boot_complete: bool,
+    /// Boot health confirmation sent to UpdateService
+    pub boot_health_reported: bool,
+    /// Services draining before a restart: service_pid → drain info
+    pub draining: BTreeMap<u32, DrainingService>,
+    /// Restart state of services and apps that have exited: name → backoff info
+    pub supervised: BTreeMap<String, SupervisedService>,
+    /// Names of processes registered for the supervisor: pid → name
+    pub process_names: BTreeMap<u32, String>,
+    /// Processes the supervisor asked to stop; their exit is not a crash
+    pub stopping: BTreeSet<u32>,
+    /// Sender of the message being handled, if any
+    #[cfg(feature = "debug-invariants")]
+    pub handling: Option<u32>,
 }
 
 impl Init {
@@ -148,9 +213,21 @@ impl Init {
             services: BTreeMap::new(),
             service_cap_slots: BTreeMap::new(),
             service_vfs_slots: BTreeMap::new(),
+            lookup_reply_slots: BTreeMap::new(),
             pending_deliveries: BTreeMap::new(),
             endpoint_slot: INIT_ENDPOINT_SLOT,
+            boot_states: boot_graph::BOOT_SERVICES
+                .iter()
+                .map(|_| boot_graph::BootState::Pending)
+                .collect(),
             boot_complete: false,
+            boot_health_reported: false,
+            draining: BTreeMap::new(),
+            supervised: BTreeMap::new(),
+            process_names: BTreeMap::new(),
+            stopping: BTreeSet::new(),
+            #[cfg(feature = "debug-invariants")]
+            handling: None,
         }
     }
 
@@ -160,6 +237,7 @@ impl Init {
     }
 
     /// Run the init process
+    #[cfg(not(test))]
     fn run(&mut self) {
         self.log("Zero OS Init Process starting (PID 1)");
         self.log("Service registry initialized");
@@ -169,7 +247,8 @@ impl Init {
 
         self.log("Entering idle loop...");
 
-        // Minimal loop: handle service messages
+        // Minimal loop: handle service messages and finish the boot as
+        // dependencies become ready
         loop {
             match syscall::receive(self.endpoint_slot) {
                 Ok(msg) => {
@@ -183,6 +262,9 @@ impl Init {
                     self.log(&format!("AGENT_LOG:receive_error:{:?}", e));
                 }
             }
+            self.advance_boot();
+            self.check_drain_deadlines();
+            self.check_restart_schedule();
             syscall::yield_now();
         }
     }
@@ -193,7 +275,12 @@ impl Init {
             "AGENT_LOG:handle_message:tag=0x{:x}:from_pid={}:len={}",
             msg.tag, msg.from_pid, msg.data.len()
         ));
-        
+
+        #[cfg(feature = "debug-invariants")]
+        {
+            self.handling = Some(msg.from_pid);
+        }
+
         match msg.tag {
             // Service registry protocol
             MSG_REGISTER_SERVICE => self.handle_register(msg),
@@ -217,6 +304,12 @@ impl Init {
                 self.handle_service_cap_preregister(msg);
             }
             MSG_VFS_RESPONSE_CAP_GRANTED => self.handle_vfs_response_cap_granted(msg),
+            MSG_LOOKUP_REPLY_CAP_GRANTED => self.handle_lookup_reply_cap_granted(msg),
+            MSG_SERVICE_DRAINED => self.handle_service_drained(msg),
+            MSG_SUPERVISOR_RESTART_SERVICE => self.handle_supervisor_restart_service(msg),
+            MSG_PROCESS_EXITED => self.handle_process_exited(msg),
+            MSG_PROCESS_OOM => self.handle_process_oom(msg),
+            MSG_SUPERVISOR_RESET_CRASHES => self.handle_supervisor_reset_crashes(msg),
 
             // Init-driven spawn protocol (supervisor → Init)
             MSG_SUPERVISOR_SPAWN_PROCESS => self.handle_supervisor_spawn_process(msg),
@@ -230,6 +323,11 @@ impl Init {
                 ));
             }
         }
+
+        #[cfg(feature = "debug-invariants")]
+        {
+            self.handling = None;
+        }
     }
 }
 
@@ -238,6 +336,7 @@ impl Init {
 // =============================================================================
 
 /// Process entry point - called by the Web Worker
+#[cfg(not(test))]
 #[no_mangle]
 pub extern "C" fn _start() {
     let mut init = Init::new();
@@ -14,6 +14,7 @@ use std::string::String;
 
 use crate::Init;
 use zos_process as syscall;
+use zos_process::MSG_LOOKUP_RESPONSE;
 
 impl Init {
     /// Handle service registration
@@ -63,7 +64,14 @@ impl Init {
             name, msg.from_pid, endpoint_id
         ));
 
+        // Let processes reach the service by name; a restarted service
+        // re-registers here, which moves everyone over to the new instance
+        if let Err(e) = syscall::bind_name(&name, msg.from_pid) {
+            self.log(&format!("Register: kernel name bind for '{}' failed: {}", name, e));
+        }
+
         self.services.insert(name, info);
+        self.check_boot_health();
     }
 
     /// Handle service lookup
@@ -100,12 +108,35 @@ impl Init {
             found != 0
         ));
 
-        // Send response via debug channel
-        let response_msg = format!(
-            "INIT:LOOKUP_RESPONSE:{}:{}:{}",
-            msg.from_pid, found, endpoint_id
-        );
-        syscall::debug(&response_msg);
+        let reply_slot = match self.lookup_reply_slots.get(&msg.from_pid) {
+            Some(&slot) => slot,
+            None => {
+                self.log(&format!(
+                    "Lookup: PID {} has no lookup-reply endpoint",
+                    msg.from_pid
+                ));
+                return;
+            }
+        };
+
+        // Response: [found: u8, endpoint_id_low: u32, endpoint_id_high: u32]
+        let mut response = [0u8; 9];
+        response[0] = found;
+        response[1..5].copy_from_slice(&(endpoint_id as u32).to_le_bytes());
+        response[5..9].copy_from_slice(&((endpoint_id >> 32) as u32).to_le_bytes());
+        if let Err(e) = syscall::send(reply_slot, MSG_LOOKUP_RESPONSE, &response) {
+            self.log(&format!(
+                "Lookup: reply to PID {} failed: error {}",
+                msg.from_pid, e
+            ));
+        }
+    }
+
+    /// Drop the lookup-reply capability of a process that is gone.
+    pub fn forget_lookup_reply(&mut self, pid: u32) {
+        if let Some(slot) = self.lookup_reply_slots.remove(&pid) {
+            let _ = syscall::cap_delete(slot);
+        }
     }
 
     /// Handle service ready notification
@@ -11,11 +11,12 @@
 //!
 //! | Range | Category |
 //! |-------|----------|
-//! | 0x01-0x0F | Misc (debug, time, info) |
+//! | 0x01-0x0F | Misc (debug, time, timers) |
 //! | 0x10-0x1F | Process (create, exit, kill) |
 //! | 0x30-0x3F | Capability (grant, revoke, inspect) |
-//! | 0x40-0x4F | IPC (send, receive, call, reply) |
+//! | 0x40-0x4F | IPC (send, receive, call, reply, notifications, broadcast) |
 //! | 0x50-0x5F | System (list processes) |
+//! | 0x60-0x6F | Shared memory |
 //! | 0x70-0x7F | Platform Storage (async ops) |
 //! | 0x80-0x8F | Keystore (async key storage) |
 //! | 0x90-0x9F | Network (async HTTP) |
@@ -25,19 +26,33 @@
 //! | Range         | Service                              |
 //! |---------------|--------------------------------------|
 //! | 0x0001-0x000F | Console / System                     |
-//! | 0x0080        | Storage result (async IPC)           |
+//! | 0x0010-0x001F | Request control (any service)        |
+//! | 0x0080-0x0082 | Storage/keystore results (async IPC) |
 //! | 0x1000-0x100F | Init service protocol                |
 //! | 0x1010-0x101F | Permission protocol (legacy)         |
 //! | 0x2000-0x200F | App protocol (state, input, etc.)    |
 //! | 0x2001-0x200F | Supervisor → Init protocol           |
 //! | 0x2010-0x201F | PermissionService protocol           |
-//! | 0x2020        | Supervisor → PermissionService       |
+//! | 0x2020-0x2021 | Supervisor → PermissionService       |
 //! | 0x3000-0x30FF | Kernel notifications                 |
 //! | 0x4000-0x4FFF | System diagnostics (memhog, etc.)    |
 //! | 0x5000-0x50FF | Identity permission checks           |
 //! | 0x7000-0x70FF | Identity service                     |
 //! | 0x8000-0x80FF | VFS service                          |
 //! | 0x8100-0x810F | Time service                         |
+//! | 0x8200-0x820F | Update service                       |
+//! | 0x8300-0x830F | Feature flag service                 |
+//! | 0x8400-0x840F | Desktop automation                   |
+//! | 0x8500-0x850F | Speech service                       |
+//! | 0x8600-0x860F | Event bus service                    |
+//! | 0x8700-0x870F | Calendar service                     |
+//! | 0x8800-0x880F | Contacts (address book) service      |
+//! | 0x8900-0x890F | Messaging service                    |
+//! | 0x8A00-0x8A0F | Print service                        |
+//! | 0x8B00-0x8B0F | Thumbnail service                    |
+//! | 0x8C00-0x8C0F | Archive service                      |
+//! | 0x8D00-0x8D0F | Command palette service              |
+//! | 0x8E00-0x8E0F | Clipboard service                    |
 //! | 0x9000-0x901F | Network service                      |
 //! | 0xA000-0xA0FF | Keystore service                     |
 //!
@@ -58,6 +73,8 @@
 
 #![no_std]
 
+extern crate alloc;
+
 // =============================================================================
 // Object Types (Canonical definition for capabilities)
 // =============================================================================
@@ -98,6 +115,10 @@ pub enum ObjectType {
     Identity = 10,
     /// Cryptographic keystore - for secure key storage
     Keystore = 11,
+    /// Notification - signal bits set without sending a message
+    Notification = 12,
+    /// Debugger - for reading other processes' memory and runtime state
+    Debugger = 13,
 }
 
 impl ObjectType {
@@ -117,6 +138,8 @@ impl ObjectType {
             9 => Some(ObjectType::Filesystem),
             10 => Some(ObjectType::Identity),
             11 => Some(ObjectType::Keystore),
+            12 => Some(ObjectType::Notification),
+            13 => Some(ObjectType::Debugger),
             _ => None,
         }
     }
@@ -135,6 +158,8 @@ impl ObjectType {
             ObjectType::Filesystem => "Filesystem",
             ObjectType::Identity => "Identity",
             ObjectType::Keystore => "Keystore",
+            ObjectType::Notification => "Notification",
+            ObjectType::Debugger => "Debugger",
         }
     }
 }
@@ -162,6 +187,16 @@ pub mod syscall {
     pub const SYS_WALLCLOCK: u32 = 0x06;
     /// Console write syscall - write text to console output
     pub const SYS_CONSOLE_WRITE: u32 = 0x07;
+    /// Arm a timer that queues `kernel::MSG_TIMER_FIRED` on one of the
+    /// caller's endpoints.
+    /// arg1 = delay (ms), arg2 = period (ms, 0 = one-shot),
+    /// arg3 = endpoint slot (needs read permission)
+    /// Returns: timer ID (>0), or -1
+    pub const SYS_TIMER_CREATE: u32 = 0x08;
+    /// Cancel one of the caller's timers.
+    /// arg1 = timer ID
+    /// Returns: 0, or -1 if there is no such timer
+    pub const SYS_TIMER_CANCEL: u32 = 0x09;
 
     // === Process (0x10 - 0x1F) ===
     /// Create an IPC endpoint
@@ -169,8 +204,18 @@ pub mod syscall {
     /// Delete an endpoint
     pub const SYS_DELETE_ENDPOINT: u32 = 0x12;
     /// Kill a process (requires Process capability with kill permission)
+    /// arg1 = target PID, or a process group ID with arg2 = `KILL_GROUP`
+    /// A group kill spares the caller and kills the members it may kill:
+    /// all of them for Init or a member of the group, otherwise those it
+    /// holds a Process capability for.
+    /// Returns: 0, or -1 on error; for a group, the number of processes
+    /// killed, or `syscall_error::NOT_FOUND` or `PERMISSION_DENIED`
     pub const SYS_KILL: u32 = 0x13;
+    /// `SYS_KILL` arg2: kill a process group rather than one process.
+    pub const KILL_GROUP: u32 = 1;
     /// Register a new process (Init-only syscall for spawn protocol)
+    /// arg1 = name length, arg2 = process group to join (0 = its own)
+    /// Payload: [name: UTF-8 bytes]
     pub const SYS_REGISTER_PROCESS: u32 = 0x14;
     /// Create an endpoint for another process (Init-only syscall for spawn protocol)
     pub const SYS_CREATE_ENDPOINT_FOR: u32 = 0x15;
@@ -184,6 +229,66 @@ pub mod syscall {
     /// Payload: [name_len: u32 (LE), name: [u8], binary: [u8]]
     /// Returns: PID on success (>0), negative error code on failure
     pub const SYS_SPAWN_PROCESS: u32 = 0x17;
+    /// Bind a service name to a process's input endpoint (Init-only).
+    /// arg1 = service PID
+    /// Payload: [name: UTF-8 bytes]
+    /// Rebinding a name invalidates every cached resolution of it.
+    pub const SYS_BIND_NAME: u32 = 0x18;
+    /// Create a broadcast endpoint: messages published to it are copied to
+    /// every subscribed endpoint.
+    /// Returns: slot of an Endpoint capability with full permissions, or
+    /// `syscall_error::LIMIT_EXCEEDED`
+    pub const SYS_BROADCAST_CREATE: u32 = 0x19;
+    /// Set a process's scheduling priority class (see [`crate::priority`]).
+    /// arg1 = target PID, arg2 = priority class
+    /// Requires a Process capability for the target with write permission;
+    /// Init may set any priority and a process may lower its own.
+    /// Returns: 0, or `syscall_error::PERMISSION_DENIED`, `NOT_FOUND` or
+    /// `INVALID_ARGUMENT`
+    pub const SYS_SET_PRIORITY: u32 = 0x1A;
+    /// Set a process's memory limit. A process whose memory grows past its
+    /// limit is terminated, and Init is sent `init::MSG_PROCESS_OOM`.
+    /// arg1 = target PID, arg2 = limit in KiB (0 = the kernel default)
+    /// Authority as for `SYS_SET_PRIORITY`: a process may lower its own.
+    /// Returns: 0, or `syscall_error::PERMISSION_DENIED`, `NOT_FOUND` or
+    /// `INVALID_ARGUMENT` (below the process's current size)
+    pub const SYS_SET_MEMORY_LIMIT: u32 = 0x1B;
+    /// Snapshot another process's runtime state, for a debugger.
+    /// arg1 = target PID
+    /// Requires a Debugger capability with read permission.
+    /// Returns: 0 with the snapshot in the syscall result buffer:
+    /// `[state: u8, priority: u8, waiting: u8, last_syscall: u32,
+    /// memory_size: u64, memory_limit: u64, syscall_count: u64,
+    /// ipc_sent: u64, ipc_received: u64, run_time_ns: u64, wakeups: u64,
+    /// last_active_ns: u64, capabilities: u32, endpoints: u32,
+    /// queued_messages: u32, name: [u8]]` (all LE, name to the end);
+    /// waiting=1 when the process's last syscall left it waiting for a
+    /// message, signal or reply. Or `syscall_error::PERMISSION_DENIED` or
+    /// `NOT_FOUND`
+    pub const SYS_PROC_STATE: u32 = 0x1C;
+    /// Read another process's memory, for a debugger.
+    /// arg1 = target PID, arg2 = address, arg3 = length (at most
+    /// `PROC_READ_MEM_MAX`)
+    /// Requires a Debugger capability with read permission.
+    /// Returns: number of bytes read, with the bytes in the syscall result
+    /// buffer, or `syscall_error::PERMISSION_DENIED`, `NOT_FOUND`,
+    /// `INVALID_ARGUMENT` (past the end of the process's memory or too
+    /// long) or `NOT_SUPPORTED` (the platform cannot read it)
+    pub const SYS_PROC_READ_MEM: u32 = 0x1D;
+    /// Most bytes one `SYS_PROC_READ_MEM` reads.
+    pub const PROC_READ_MEM_MAX: u32 = 8192;
+    /// Move a process to another process group. A group's ID is the PID
+    /// of its leader; every process starts as the leader of its own group
+    /// unless it was spawned into its spawner's.
+    /// arg1 = target PID, arg2 = group ID (0 = a new group led by the
+    /// target)
+    /// Moving a process needs authority over it (itself, Init, a member
+    /// of its group or a Process capability for it); joining an existing
+    /// group also needs authority over that group (Init, a member, or a
+    /// Process capability for its leader).
+    /// Returns: the group ID, or `syscall_error::PERMISSION_DENIED` or
+    /// `NOT_FOUND` (no such process or group)
+    pub const SYS_SETPGID: u32 = 0x1E;
 
     // === Capability (0x30 - 0x3F) ===
     /// Grant a capability to another process
@@ -204,16 +309,136 @@ pub mod syscall {
     pub const SYS_SEND: u32 = 0x40;
     /// Receive a message
     pub const SYS_RECV: u32 = 0x41;
-    /// Call (send + wait for reply)
+    /// Send a request and wait for the reply. Whoever receives the request
+    /// holds the one-shot right to answer it with `SYS_REPLY`; the caller
+    /// collects the outcome with `SYS_CALL_WAIT`. One call at a time.
+    /// arg1 = endpoint slot, arg2 = tag, arg3 = timeout (ms, 0 = none)
+    /// Payload: request data
+    /// Returns: 0 once sent, `syscall_error::BUSY` if a call is already in
+    /// progress, `WOULD_BLOCK` if the endpoint is full, or -1
     pub const SYS_CALL: u32 = 0x42;
-    /// Reply to a call
+    /// Reply to a call received from a process.
+    /// arg1 = caller PID, arg2 = tag
+    /// Payload: reply data
+    /// Returns: 0, or `syscall_error::NOT_FOUND` if the sender holds no
+    /// reply capability for that caller (it did not receive its request,
+    /// already replied, or the call ended)
     pub const SYS_REPLY: u32 = 0x43;
-    /// Send with capability transfer
+    /// Send with capability transfer. The capabilities leave the sender's
+    /// CSpace and are installed in the receiver's when it receives.
+    /// arg1 = endpoint slot, arg2 = tag,
+    /// arg3 = data length | (capability count << 16)
+    /// Payload: [data: [u8], cap_slots: [u32 (LE)]]
+    /// Returns: 0, `syscall_error::WOULD_BLOCK` if the endpoint is full, or -1
     pub const SYS_SEND_CAP: u32 = 0x44;
+    /// Send to a service by name; the kernel resolves the name to a
+    /// capability on first use and caches it.
+    /// arg1 = tag
+    /// Payload: [name_len: u8, name: [u8], data: [u8]]
+    pub const SYS_SEND_NAMED: u32 = 0x45;
+    /// Set the deadline (uptime nanos) stamped on messages this process
+    /// sends; 0 clears it. Receiving a message replaces it with the
+    /// message's deadline.
+    /// arg1 = deadline low 32 bits, arg2 = high 32 bits
+    pub const SYS_SET_DEADLINE: u32 = 0x46;
+    /// Get this process's current deadline (0 = none).
+    /// arg1 = 0 for the low 32 bits, 1 for the high 32 bits
+    pub const SYS_GET_DEADLINE: u32 = 0x47;
+    /// Set the correlation ID stamped on messages this process sends; 0
+    /// clears it. Receiving a message replaces it with the message's
+    /// correlation ID, so a reply echoes the request's.
+    /// arg1 = correlation ID
+    pub const SYS_SET_CORRELATION: u32 = 0x48;
+    /// Get this process's current correlation ID (0 = none).
+    pub const SYS_GET_CORRELATION: u32 = 0x49;
+    /// Collect the outcome of this process's call.
+    /// Returns: 1 with the reply in the syscall result buffer (in the
+    /// `frame` format), 0 while waiting, `syscall_error::TIMED_OUT` or
+    /// `PEER_DIED` if the call failed, or `NOT_FOUND` if there is no call.
+    /// Any result but 0 ends the call.
+    pub const SYS_CALL_WAIT: u32 = 0x4A;
+    /// Create a notification: 31 signal bits (`NOTIFY_BITS`) that holders
+    /// of its capability set and collect without allocating a message.
+    /// Returns: slot of a Notification capability with full permissions,
+    /// or `syscall_error::LIMIT_EXCEEDED`
+    pub const SYS_NOTIFY_CREATE: u32 = 0x4B;
+    /// OR bits into a notification's pending signals (write permission).
+    /// arg1 = notification slot, arg2 = bits
+    /// Returns: 0, `syscall_error::INVALID_ARGUMENT` for no bits or bits
+    /// outside `NOTIFY_BITS`, or
+    /// `NOT_FOUND` / `PERMISSION_DENIED` for the capability
+    pub const SYS_SIGNAL: u32 = 0x4C;
+    /// Take a notification's pending signals, clearing them (read
+    /// permission).
+    /// arg1 = notification slot
+    /// Returns: the bits that were pending, 0 if none (poll again), or
+    /// `syscall_error::NOT_FOUND` / `PERMISSION_DENIED`
+    pub const SYS_WAIT: u32 = 0x4D;
+    /// Bits a notification carries. Bit 31 is reserved so that a
+    /// `SYS_WAIT` result never looks like an error code.
+    pub const NOTIFY_BITS: u32 = 0x7FFF_FFFF;
+    /// Subscribe one of this process's endpoints to a broadcast endpoint
+    /// (read permission on both), replacing any earlier subscription.
+    /// arg1 = broadcast slot, arg2 = endpoint slot, or `UNSUBSCRIBE`
+    /// Returns: 0, `syscall_error::LIMIT_EXCEEDED` if the broadcast has all
+    /// the subscribers it can take, `INVALID_ARGUMENT` if either endpoint
+    /// is of the wrong kind, or `NOT_FOUND` / `PERMISSION_DENIED` for the
+    /// capabilities
+    pub const SYS_SUBSCRIBE: u32 = 0x4E;
+    /// Publish a message to every subscriber of a broadcast endpoint
+    /// (write permission). A subscriber with too many of its messages
+    /// still queued misses it; publishing never blocks.
+    /// arg1 = broadcast slot, arg2 = tag
+    /// Payload: message data
+    /// Returns: number of subscribers the message was queued for,
+    /// `syscall_error::INVALID_ARGUMENT` if it is too large, or
+    /// `NOT_FOUND` / `PERMISSION_DENIED` for the capability
+    pub const SYS_PUBLISH: u32 = 0x4F;
+    /// `SYS_SUBSCRIBE` endpoint slot that cancels the subscription.
+    pub const UNSUBSCRIBE: u32 = u32::MAX;
 
     // === System (0x50 - 0x5F) ===
     /// List all processes (supervisor only)
+    /// arg1 = 0, or `PS_USAGE` for usage figures with each process
+    /// Returns: 0 with the list in the syscall result buffer:
+    /// `[count: u32]`, then per process `[pid: u32, name_len: u16, name]`,
+    /// with `PS_USAGE` followed by `[state: u8, priority: u8,
+    /// memory_size: u64, syscall_count: u64, ipc_sent: u64,
+    /// ipc_received: u64, run_time_ns: u64, wakeups: u64]` (all LE)
     pub const SYS_PS: u32 = 0x50;
+    /// `SYS_PS` argument asking for usage figures with each process.
+    pub const PS_USAGE: u32 = 1;
+
+    // === Shared memory (0x60 - 0x6F) ===
+    // A region is reached through a Memory capability, which can be granted
+    // or sent like any other. Mapping it gives the caller a window into the
+    // region at an address in its own memory; flush and fetch move bytes
+    // between the window and the region, so bulk data never goes through
+    // an IPC message. A mapping lasts until unmapped or the process exits,
+    // even after its capability has been sent on.
+    /// Create a zeroed shared memory region.
+    /// arg1 = size (bytes, at most 1 MiB)
+    /// Returns: slot of a Memory capability with full permissions, or
+    /// `syscall_error::INVALID_ARGUMENT` / `LIMIT_EXCEEDED`
+    pub const SYS_SHM_CREATE: u32 = 0x60;
+    /// Map a region at an address in the caller's memory. Needs read
+    /// permission; the mapping is writable if the capability has write.
+    /// arg1 = Memory capability slot, arg2 = address, arg3 = length
+    /// Returns: mapped length (the smaller of the length and the region
+    /// size), or a negative `syscall_error`
+    pub const SYS_SHM_MAP: u32 = 0x61;
+    /// Remove the mapping at an address.
+    /// arg1 = address
+    /// Returns: 0, or `syscall_error::NOT_FOUND`
+    pub const SYS_SHM_UNMAP: u32 = 0x62;
+    /// Copy bytes of a writable mapping into the region.
+    /// arg1 = mapping address, arg2 = offset, arg3 = length
+    /// Returns: 0, or a negative `syscall_error`
+    pub const SYS_SHM_FLUSH: u32 = 0x63;
+    /// Copy bytes of the region into a mapping.
+    /// arg1 = mapping address, arg2 = offset, arg3 = length
+    /// Returns: 0, or a negative `syscall_error`
+    pub const SYS_SHM_FETCH: u32 = 0x64;
 
     // === Platform Storage (0x70 - 0x7F) ===
     // HAL-level key-value storage operations. VfsService uses these for persistence.
@@ -228,6 +453,10 @@ pub mod syscall {
     pub const SYS_STORAGE_LIST: u32 = 0x73;
     /// Check if key exists (async - returns request_id)
     pub const SYS_STORAGE_EXISTS: u32 = 0x74;
+    /// Cancel a pending storage request (arg1 = request_id).
+    /// The request completes with `storage::result::CANCELLED` unless its
+    /// result was already on the way.
+    pub const SYS_STORAGE_CANCEL: u32 = 0x7A;
     /// Batch write multiple key-value pairs (async - returns request_id)
     /// Used by VFS mkdir with create_parents=true to write all parent inodes atomically.
     /// Payload: [count: u32, (key_len: u32, key: [u8], value_len: u32, value: [u8])*]
@@ -296,11 +525,119 @@ pub mod app {
     /// App → UI: Error notification.
     /// The app reports an error to the UI for display.
     pub const MSG_APP_ERROR: u32 = 0x2004;
+
+    /// App → UI: Window properties.
+    /// The app updates its window's resize constraints (aspect-ratio lock,
+    /// resize increments). Payload: `WindowProperties` envelope.
+    pub const MSG_APP_WINDOW_PROPERTIES: u32 = 0x2005;
 }
 
 // Re-export console constants at crate root for convenience
 pub use console::MSG_CONSOLE_INPUT;
 
+// =============================================================================
+// Request Control (0x0010 - 0x001F)
+// =============================================================================
+
+/// Messages any request/response service understands.
+pub mod request {
+    /// Cancel in-flight requests from the sender.
+    /// Payload: [request_tag: u32] - the tag the request was sent with,
+    /// or 0 for every request the sender has in flight.
+    ///
+    /// No reply is sent for the cancel itself. Each cancelled request gets
+    /// its usual response (request tag + 1) carrying a `Cancelled` error.
+    pub const MSG_CANCEL_REQUEST: u32 = 0x0010;
+
+    /// Cancel every in-flight request, whatever its tag.
+    pub const CANCEL_ALL: u32 = 0;
+}
+
+// =============================================================================
+// Received Message Framing
+// =============================================================================
+
+/// Layout of a message as `SYS_RECV` hands it to the receiver.
+///
+/// Wire format: [from_pid: u32, tag: u32, num_caps: u8,
+/// cap_slots: [u32; num_caps], correlation_id: u32, data: [u8]]
+///
+/// All integers are little-endian. A correlation ID of 0 means the sender
+/// set none.
+pub mod frame {
+    use alloc::vec::Vec;
+
+    /// ID a client stamps on a request for the service to echo in its
+    /// response, so the client can pair them.
+    pub type CorrelationId = u32;
+
+    /// A received message, borrowing its payload.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Frame<'a> {
+        /// Sender's PID
+        pub from_pid: u32,
+        /// Message tag
+        pub tag: u32,
+        /// Receiver's slots holding the capabilities transferred with it
+        pub cap_slots: Vec<u32>,
+        /// Correlation ID the sender set, if any
+        pub correlation_id: Option<CorrelationId>,
+        /// Payload
+        pub data: &'a [u8],
+    }
+
+    /// Why a frame could not be decoded.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum FrameError {
+        /// Shorter than its header (cap slots included)
+        TooShort,
+    }
+
+    impl<'a> Frame<'a> {
+        /// Size of the header without cap slots.
+        pub const MIN_HEADER_LEN: usize = 13;
+
+        /// Encode to the wire format. At most 255 cap slots are kept.
+        pub fn encode(&self) -> Vec<u8> {
+            let num_caps = self.cap_slots.len().min(u8::MAX as usize);
+            let mut buf = Vec::with_capacity(Self::MIN_HEADER_LEN + num_caps * 4 + self.data.len());
+            buf.extend_from_slice(&self.from_pid.to_le_bytes());
+            buf.extend_from_slice(&self.tag.to_le_bytes());
+            buf.push(num_caps as u8);
+            for slot in &self.cap_slots[..num_caps] {
+                buf.extend_from_slice(&slot.to_le_bytes());
+            }
+            buf.extend_from_slice(&self.correlation_id.unwrap_or(0).to_le_bytes());
+            buf.extend_from_slice(self.data);
+            buf
+        }
+
+        /// Decode from the wire format, borrowing the payload.
+        pub fn decode(buf: &'a [u8]) -> Result<Self, FrameError> {
+            let u32_at = |at: usize| -> Result<u32, FrameError> {
+                buf.get(at..at + 4)
+                    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .ok_or(FrameError::TooShort)
+            };
+            let from_pid = u32_at(0)?;
+            let tag = u32_at(4)?;
+            let num_caps = *buf.get(8).ok_or(FrameError::TooShort)? as usize;
+            let cap_slots = (0..num_caps)
+                .map(|i| u32_at(9 + i * 4))
+                .collect::<Result<Vec<_>, _>>()?;
+            let correlation_at = 9 + num_caps * 4;
+            let correlation_id = u32_at(correlation_at)?;
+            Ok(Self {
+                from_pid,
+                tag,
+                cap_slots,
+                correlation_id: (correlation_id != 0).then_some(correlation_id),
+                data: &buf[correlation_at + 4..],
+            })
+        }
+    }
+}
+
 // =============================================================================
 // Storage Result (0x0080)
 // =============================================================================
@@ -311,6 +648,11 @@ pub mod storage {
     /// Payload format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
     pub const MSG_STORAGE_RESULT: u32 = 0x80;
 
+    /// Several storage results for the same process in one delivery.
+    /// Payload format: [count: u32, (len: u32, result: [u8; len])*]
+    /// where each result has the MSG_STORAGE_RESULT payload format.
+    pub const MSG_STORAGE_RESULT_BATCH: u32 = 0x82;
+
     /// Storage result types
     pub mod result {
         /// Read succeeded, data follows
@@ -325,6 +667,172 @@ pub mod storage {
         pub const LIST_OK: u8 = 4;
         /// Exists check result: 1 = exists, 0 = not exists
         pub const EXISTS_OK: u8 = 5;
+        /// Request was cancelled before it completed
+        pub const CANCELLED: u8 = 6;
+    }
+
+    /// Request ID correlating an async storage or keystore syscall with
+    /// its result.
+    pub type RequestId = u32;
+
+    /// Kind of a storage or keystore result.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum ResultKind {
+        /// Read succeeded, data follows
+        ReadOk = result::READ_OK,
+        /// Write/delete succeeded
+        WriteOk = result::WRITE_OK,
+        /// Key not found
+        NotFound = result::NOT_FOUND,
+        /// Operation failed, error message follows
+        Error = result::ERROR,
+        /// List succeeded, key list follows (JSON array)
+        ListOk = result::LIST_OK,
+        /// Exists check result: 1 = exists, 0 = not exists
+        ExistsOk = result::EXISTS_OK,
+        /// Request was cancelled before it completed
+        Cancelled = result::CANCELLED,
+    }
+
+    impl ResultKind {
+        /// Convert from the wire value.
+        ///
+        /// Returns `None` for unknown values.
+        pub fn from_u8(value: u8) -> Option<Self> {
+            match value {
+                result::READ_OK => Some(ResultKind::ReadOk),
+                result::WRITE_OK => Some(ResultKind::WriteOk),
+                result::NOT_FOUND => Some(ResultKind::NotFound),
+                result::ERROR => Some(ResultKind::Error),
+                result::LIST_OK => Some(ResultKind::ListOk),
+                result::EXISTS_OK => Some(ResultKind::ExistsOk),
+                result::CANCELLED => Some(ResultKind::Cancelled),
+                _ => None,
+            }
+        }
+
+        /// Get human-readable name for logging.
+        pub fn name(&self) -> &'static str {
+            match self {
+                ResultKind::ReadOk => "READ_OK",
+                ResultKind::WriteOk => "WRITE_OK",
+                ResultKind::NotFound => "NOT_FOUND",
+                ResultKind::Error => "ERROR",
+                ResultKind::ListOk => "LIST_OK",
+                ResultKind::ExistsOk => "EXISTS_OK",
+                ResultKind::Cancelled => "CANCELLED",
+            }
+        }
+    }
+
+    /// Why a result payload could not be decoded.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum DecodeError {
+        /// Shorter than the fixed header
+        TooShort,
+        /// Result type byte is not a known `ResultKind`
+        UnknownKind(u8),
+        /// Header declares more data than the payload holds
+        Truncated {
+            /// data_len from the header
+            declared: usize,
+            /// Bytes actually following the header
+            available: usize,
+        },
+    }
+
+    /// A storage or keystore result, as carried by MSG_STORAGE_RESULT and
+    /// MSG_KEYSTORE_RESULT.
+    ///
+    /// Wire format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct StorageResult<'a> {
+        /// Request ID returned by the syscall that started the operation
+        pub request_id: RequestId,
+        /// What happened
+        pub kind: ResultKind,
+        /// Read data, key list, exists flag or error message
+        pub data: &'a [u8],
+    }
+
+    impl<'a> StorageResult<'a> {
+        /// Size of the fixed header preceding `data`.
+        pub const HEADER_LEN: usize = 9;
+
+        /// Create a result.
+        pub fn new(request_id: RequestId, kind: ResultKind, data: &'a [u8]) -> Self {
+            Self {
+                request_id,
+                kind,
+                data,
+            }
+        }
+
+        /// Create an `ExistsOk` result carrying `exists`.
+        pub fn exists(request_id: RequestId, exists: bool) -> StorageResult<'static> {
+            let data: &'static [u8] = if exists { &[1] } else { &[0] };
+            StorageResult::new(request_id, ResultKind::ExistsOk, data)
+        }
+
+        /// For `ExistsOk`, whether the key exists.
+        pub fn exists_flag(&self) -> bool {
+            self.data.first() == Some(&1)
+        }
+
+        /// Encode to the wire format.
+        pub fn encode(&self) -> alloc::vec::Vec<u8> {
+            let mut payload = alloc::vec::Vec::with_capacity(Self::HEADER_LEN + self.data.len());
+            payload.extend_from_slice(&self.request_id.to_le_bytes());
+            payload.push(self.kind as u8);
+            payload.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+            payload.extend_from_slice(self.data);
+            payload
+        }
+
+        /// Decode from the wire format, borrowing the data.
+        pub fn decode(payload: &'a [u8]) -> Result<Self, DecodeError> {
+            if payload.len() < Self::HEADER_LEN {
+                return Err(DecodeError::TooShort);
+            }
+            let request_id = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
+            let kind =
+                ResultKind::from_u8(payload[4]).ok_or(DecodeError::UnknownKind(payload[4]))?;
+            let declared =
+                u32::from_le_bytes([payload[5], payload[6], payload[7], payload[8]]) as usize;
+            let available = payload.len() - Self::HEADER_LEN;
+            if declared > available {
+                return Err(DecodeError::Truncated {
+                    declared,
+                    available,
+                });
+            }
+            Ok(Self {
+                request_id,
+                kind,
+                data: &payload[Self::HEADER_LEN..Self::HEADER_LEN + declared],
+            })
+        }
+    }
+
+    /// Split a MSG_STORAGE_RESULT_BATCH payload into its result payloads.
+    ///
+    /// Returns `None` if the count or any entry length overruns the payload.
+    pub fn split_result_batch(payload: &[u8]) -> Option<alloc::vec::Vec<&[u8]>> {
+        let count = u32::from_le_bytes(payload.get(0..4)?.try_into().ok()?) as usize;
+        let mut rest = &payload[4..];
+        // Each entry takes at least its 4-byte length
+        if count > rest.len() / 4 {
+            return None;
+        }
+        let mut results = alloc::vec::Vec::with_capacity(count);
+        for _ in 0..count {
+            let len = u32::from_le_bytes(rest.get(0..4)?.try_into().ok()?) as usize;
+            let result = rest.get(4..4usize.checked_add(len)?)?;
+            results.push(result);
+            rest = &rest[4 + len..];
+        }
+        Some(results)
     }
 }
 
@@ -335,23 +843,12 @@ pub mod storage {
 pub mod keystore {
     /// Keystore operation result delivered via IPC.
     /// Payload format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
-    /// Uses same format as MSG_STORAGE_RESULT for consistency.
+    /// Uses same format as MSG_STORAGE_RESULT; see `storage::StorageResult`.
     pub const MSG_KEYSTORE_RESULT: u32 = 0x81;
 
-    /// Keystore result types (same as storage for consistency)
+    /// Keystore result types (the storage result types)
     pub mod result {
-        /// Read succeeded, data follows
-        pub const READ_OK: u8 = 0;
-        /// Write/delete succeeded
-        pub const WRITE_OK: u8 = 1;
-        /// Key not found
-        pub const NOT_FOUND: u8 = 2;
-        /// Operation failed
-        pub const ERROR: u8 = 3;
-        /// List succeeded, key list follows (JSON array)
-        pub const LIST_OK: u8 = 4;
-        /// Exists check result: 1 = exists, 0 = not exists
-        pub const EXISTS_OK: u8 = 5;
+        pub use crate::storage::result::*;
     }
 }
 
@@ -371,11 +868,11 @@ pub mod init {
     /// Payload: [name_len: u8, name: [u8]]
     pub const MSG_LOOKUP_SERVICE: u32 = 0x1001;
 
-    /// Lookup response.
+    /// Lookup response (init → requester's lookup-reply endpoint).
     /// Payload: [found: u8, endpoint_id_low: u32, endpoint_id_high: u32]
     pub const MSG_LOOKUP_RESPONSE: u32 = 0x1002;
 
-    /// Request spawn.
+    /// Request spawn. The new process joins the requester's process group.
     /// Payload: [name_len: u8, name: [u8]]
     pub const MSG_SPAWN_SERVICE: u32 = 0x1003;
 
@@ -400,6 +897,35 @@ pub mod init {
     /// arriving after spawn can be delivered without waiting for async grant.
     /// Payload: [service_pid: u32, cap_slot: u32]
     pub const MSG_SERVICE_CAP_PREREGISTER: u32 = 0x1008;
+
+    /// Drain before restart (init → service).
+    /// The service stops accepting new requests (answering them with a
+    /// retryable error), finishes pending operations, checkpoints its state
+    /// and then acks with MSG_SERVICE_DRAINED.
+    /// Payload: (empty)
+    pub const MSG_SERVICE_DRAIN: u32 = 0x1009;
+
+    /// Drain complete (service → init). Init may now restart the service.
+    /// Payload: (empty)
+    pub const MSG_SERVICE_DRAINED: u32 = 0x100A;
+
+    /// Lookup-reply endpoint created notification (supervisor → init).
+    /// Init owns the endpoint and sends MSG_LOOKUP_RESPONSE to it; the
+    /// process holds a receive-only capability to it.
+    /// Payload: [process_pid: u32, cap_slot: u32]
+    pub const MSG_LOOKUP_REPLY_CAP_GRANTED: u32 = 0x100B;
+
+    /// Process exit notification (kernel → init).
+    /// Sent for every process other than Init when it exits, is killed or
+    /// faults. Kills and faults report code -1.
+    /// Payload: [pid: u32, exit_code: i32]
+    pub const MSG_PROCESS_EXITED: u32 = 0x100C;
+
+    /// Process over its memory limit (kernel → init).
+    /// Sent as the kernel terminates the process; its MSG_PROCESS_EXITED
+    /// (code -1) follows.
+    /// Payload: [pid: u32, memory_size: u64, limit: u64]
+    pub const MSG_PROCESS_OOM: u32 = 0x100D;
 }
 
 // =============================================================================
@@ -439,7 +965,9 @@ pub mod supervisor {
     pub const MSG_SUPERVISOR_CONSOLE_INPUT: u32 = 0x2001;
 
     /// Supervisor requests Init to terminate a process.
-    /// Payload: [target_pid: u32]
+    /// Payload: [target_pid: u32, crashed: u8 (optional)]
+    /// crashed=1 when the process's worker died on its own: the exit then
+    /// counts as a crash rather than a requested stop.
     pub const MSG_SUPERVISOR_KILL_PROCESS: u32 = 0x2002;
 
     /// Supervisor requests Init to route an IPC message to a process.
@@ -487,12 +1015,34 @@ pub mod supervisor {
     /// Payload: [success: u8, new_slot: u32]
     pub const MSG_SUPERVISOR_CAP_RESPONSE: u32 = 0x2009;
 
+    /// Supervisor requests Init to restart a registered service.
+    /// Init drains the service first (MSG_SERVICE_DRAIN), then kills it and
+    /// spawns it again. A service that does not drain in time is restarted
+    /// anyway.
+    /// Payload: [service_pid: u32]
+    pub const MSG_SUPERVISOR_RESTART_SERVICE: u32 = 0x200A;
+
+    /// Supervisor asks Init to forget a process name's crashes, clearing a
+    /// crash loop. A service stopped by one is restarted.
+    /// Payload: [name: [u8]] (UTF-8, to end of payload)
+    pub const MSG_SUPERVISOR_RESET_CRASHES: u32 = 0x200B;
+
     /// Supervisor requests PermissionService to revoke a capability from a process.
     /// Payload: [target_pid: u32, slot: u32, reason: u8]
     ///
     /// **IMPORTANT**: This is the canonical value (0x2020). The supervisor had
     /// a bug using 0x2010 which conflicts with MSG_REQUEST_CAPABILITY.
     pub const MSG_SUPERVISOR_REVOKE_CAP: u32 = 0x2020;
+
+    /// Supervisor requests PermissionService to delete an app's data.
+    /// PermissionService forwards it to VfsService as a signed request.
+    /// Payload: [user_id: u128, app_id: [u8]] (app_id is UTF-8, to end of payload)
+    pub const MSG_SUPERVISOR_DELETE_APP_DATA: u32 = 0x2021;
+
+    /// Supervisor answers a PermissionService prompt (`debug::PERMSVC_PROMPT`)
+    /// with the user's decision.
+    /// Payload: [prompt_id: u32, granted: u8]
+    pub const MSG_SUPERVISOR_PROMPT_RESPONSE: u32 = 0x2022;
 }
 
 // =============================================================================
@@ -502,7 +1052,8 @@ pub mod supervisor {
 /// PermissionService protocol messages.
 ///
 /// These messages are used by processes to request capabilities from
-/// the PermissionService (PID 2).
+/// the PermissionService (PID 2). The supervisor and Init additionally
+/// audit, revoke and export the grants it has recorded (JSON payloads).
 pub mod pm {
     /// Request a capability from PermissionService.
     /// Payload: [object_type: u8, object_id: u64, requested_perms: u8]
@@ -523,6 +1074,31 @@ pub mod pm {
     /// Capability list response.
     /// Payload: [count: u32, (slot: u32, type: u8, object_id: u64, perms: u8)*]
     pub const MSG_CAPS_LIST_RESPONSE: u32 = 0x2014;
+
+    /// List recorded grants, grouped by process (supervisor and Init only).
+    /// Payload: JSON `{ pid? }` (empty = every process)
+    pub const MSG_LIST_GRANTS: u32 = 0x2015;
+
+    /// Grant list response.
+    /// Payload: JSON `{ apps: [{ pid, grants: [Grant] }] }` or `{ error }`
+    pub const MSG_LIST_GRANTS_RESPONSE: u32 = 0x2016;
+
+    /// Revoke one recorded grant through capability revocation (supervisor
+    /// and Init only).
+    /// Payload: JSON `{ pid, object_type: u8 }`
+    pub const MSG_REVOKE_GRANT: u32 = 0x2017;
+
+    /// Grant revocation response.
+    /// Payload: JSON `{ revoked: Grant }` or `{ error }`
+    pub const MSG_REVOKE_GRANT_RESPONSE: u32 = 0x2018;
+
+    /// Export the whole grant table (supervisor and Init only).
+    /// Payload: (empty)
+    pub const MSG_EXPORT_GRANTS: u32 = 0x2019;
+
+    /// Grant table export.
+    /// Payload: JSON `{ version: 1, exported_at, grants: [Grant] }` or `{ error }`
+    pub const MSG_EXPORT_GRANTS_RESPONSE: u32 = 0x201A;
 }
 
 // =============================================================================
@@ -534,6 +1110,10 @@ pub mod kernel {
     /// Notification that a capability was revoked from this process.
     /// Payload: [slot: u32, object_type: u8, object_id: u64, reason: u8]
     pub const MSG_CAP_REVOKED: u32 = 0x3010;
+
+    /// A timer armed with `SYS_TIMER_CREATE` came due. Sent from PID 0.
+    /// Payload: [timer_id: u32]
+    pub const MSG_TIMER_FIRED: u32 = 0x3020;
 }
 
 /// Capability revocation reasons.
@@ -658,6 +1238,10 @@ pub mod identity_query {
     pub const MSG_WHOAMI: u32 = 0x7030;
     /// Whoami response.
     pub const MSG_WHOAMI_RESPONSE: u32 = 0x7031;
+    /// Public keys and identity key fingerprint of a user request.
+    pub const MSG_GET_KEY_FINGERPRINT: u32 = 0x7032;
+    /// Public keys and identity key fingerprint response.
+    pub const MSG_GET_KEY_FINGERPRINT_RESPONSE: u32 = 0x7033;
 }
 
 /// Identity service messages - Credentials (0x7040-0x704F).
@@ -694,6 +1278,22 @@ pub mod identity_key {
     pub const MSG_RECOVER_NEURAL_KEY: u32 = 0x7056;
     /// Recover Neural Key from shards response.
     pub const MSG_RECOVER_NEURAL_KEY_RESPONSE: u32 = 0x7057;
+    /// Export a backup shard as a QR-encodable blob request.
+    pub const MSG_EXPORT_SHARD: u32 = 0x7058;
+    /// Export a backup shard response.
+    pub const MSG_EXPORT_SHARD_RESPONSE: u32 = 0x7059;
+    /// Verify a shard set against the threshold request.
+    pub const MSG_VERIFY_SHARDS: u32 = 0x705A;
+    /// Verify a shard set response.
+    pub const MSG_VERIFY_SHARDS_RESPONSE: u32 = 0x705B;
+    /// Confirm a backup shard as backed up request.
+    pub const MSG_MARK_SHARD_BACKED_UP: u32 = 0x705C;
+    /// Confirm a backup shard response.
+    pub const MSG_MARK_SHARD_BACKED_UP_RESPONSE: u32 = 0x705D;
+    /// Get shard backup status request.
+    pub const MSG_GET_SHARD_BACKUP_STATUS: u32 = 0x705E;
+    /// Get shard backup status response.
+    pub const MSG_GET_SHARD_BACKUP_STATUS_RESPONSE: u32 = 0x705F;
 }
 
 /// Identity service messages - Machine Keys (0x7060-0x706F).
@@ -726,6 +1326,40 @@ pub mod identity_machine {
     pub const MSG_CREATE_MACHINE_KEY_AND_ENROLL_RESPONSE: u32 = 0x706B;
 }
 
+/// Identity service messages - Machine Enrollment (0x7070-0x707F).
+///
+/// A new machine joins an existing identity only once an already-enrolled
+/// machine approves it. The requesting machine keeps its key material
+/// locally; the approval is signed by the approver's machine key and
+/// relayed through the ZID server.
+pub mod identity_enroll {
+    /// Request enrollment of this machine (run on the new machine).
+    /// Payload: JSON-serialized RequestEnrollmentRequest
+    pub const MSG_REQUEST_ENROLLMENT: u32 = 0x7070;
+    /// Request enrollment response.
+    /// Payload: JSON-serialized RequestEnrollmentResponse
+    pub const MSG_REQUEST_ENROLLMENT_RESPONSE: u32 = 0x7071;
+    /// List enrollment requests awaiting a decision (run on an enrolled machine).
+    /// Payload: JSON-serialized ListPendingEnrollmentsRequest
+    pub const MSG_LIST_PENDING_ENROLLMENTS: u32 = 0x7072;
+    /// List pending enrollments response.
+    /// Payload: JSON-serialized ListPendingEnrollmentsResponse
+    pub const MSG_LIST_PENDING_ENROLLMENTS_RESPONSE: u32 = 0x7073;
+    /// Approve or reject an enrollment request (run on an enrolled machine).
+    /// Payload: JSON-serialized DecideEnrollmentRequest
+    pub const MSG_DECIDE_ENROLLMENT: u32 = 0x7074;
+    /// Decide enrollment response.
+    /// Payload: JSON-serialized DecideEnrollmentResponse
+    pub const MSG_DECIDE_ENROLLMENT_RESPONSE: u32 = 0x7075;
+    /// Collect the decision and store the machine key if approved (run on
+    /// the new machine).
+    /// Payload: JSON-serialized CompleteEnrollmentRequest
+    pub const MSG_COMPLETE_ENROLLMENT: u32 = 0x7076;
+    /// Complete enrollment response.
+    /// Payload: JSON-serialized CompleteEnrollmentResponse
+    pub const MSG_COMPLETE_ENROLLMENT_RESPONSE: u32 = 0x7077;
+}
+
 /// Identity service messages - ZID Auth (0x7080-0x708F).
 ///
 /// These messages handle authentication with the ZERO-ID remote server
@@ -786,6 +1420,12 @@ pub mod identity_prefs {
     /// Set default machine key response.
     /// Payload: JSON-serialized SetDefaultMachineKeyResponse
     pub const MSG_SET_DEFAULT_MACHINE_KEY_RESPONSE: u32 = 0x7095;
+    /// Set login items request.
+    /// Payload: JSON-serialized SetLoginItemsRequest
+    pub const MSG_SET_LOGIN_ITEMS: u32 = 0x7096;
+    /// Set login items response.
+    /// Payload: JSON-serialized SetLoginItemsResponse
+    pub const MSG_SET_LOGIN_ITEMS_RESPONSE: u32 = 0x7097;
 }
 
 // =============================================================================
@@ -830,6 +1470,10 @@ pub mod vfs_file {
     pub const MSG_VFS_COPY: u32 = 0x8018;
     /// Copy file response.
     pub const MSG_VFS_COPY_RESPONSE: u32 = 0x8019;
+    /// Hard link request.
+    pub const MSG_VFS_LINK: u32 = 0x801A;
+    /// Hard link response.
+    pub const MSG_VFS_LINK_RESPONSE: u32 = 0x801B;
 }
 
 /// VFS service messages - Metadata Operations (0x8020-0x802F).
@@ -862,6 +1506,193 @@ pub mod vfs_quota {
     pub const MSG_VFS_GET_QUOTA: u32 = 0x8032;
     /// Get quota response.
     pub const MSG_VFS_GET_QUOTA_RESPONSE: u32 = 0x8033;
+    /// Current usage and limit of a user's storage quota.
+    /// Payload: JSON {"user_id": u128}
+    pub const MSG_VFS_QUOTA_STAT: u32 = 0x8034;
+    /// Quota stat response.
+    /// Payload: JSON {"result": {"Ok": StorageQuota} | {"Err": VfsError}}
+    pub const MSG_VFS_QUOTA_STAT_RESPONSE: u32 = 0x8035;
+}
+
+/// VFS service messages - Signed Requests (0x8040-0x804F).
+///
+/// Privileged operations ordered by another service, signed with that
+/// service's key (see `zos_services::signing`).
+pub mod vfs_signed {
+    /// Signed privileged request.
+    /// Payload: JSON SignedRequest wrapping the inner operation's request.
+    /// Answered with the inner operation's response (e.g. MSG_VFS_RMDIR_RESPONSE).
+    pub const MSG_VFS_SIGNED_REQUEST: u32 = 0x8040;
+    /// Error response for a signed request that is malformed or names an
+    /// operation that cannot be signed.
+    pub const MSG_VFS_SIGNED_REQUEST_RESPONSE: u32 = 0x8041;
+    /// Register (pin) a service's public signing key. System services only.
+    /// Payload: JSON {"service": string, "public_key": hex}
+    pub const MSG_VFS_REGISTER_SERVICE_KEY: u32 = 0x8042;
+    /// Register service key response.
+    pub const MSG_VFS_REGISTER_SERVICE_KEY_RESPONSE: u32 = 0x8043;
+}
+
+/// VFS service messages - File Handles (0x8050-0x805F).
+///
+/// Partial I/O on an open file. Handles belong to the process that opened
+/// them and do not survive a VFS restart.
+pub mod vfs_handle {
+    /// Open a file. Payload: JSON OpenRequest
+    pub const MSG_VFS_OPEN: u32 = 0x8050;
+    /// Open response, carrying the new handle.
+    pub const MSG_VFS_OPEN_RESPONSE: u32 = 0x8051;
+    /// Read bytes at an offset. Payload: JSON ReadAtRequest
+    pub const MSG_VFS_READ_AT: u32 = 0x8052;
+    /// Read-at response.
+    pub const MSG_VFS_READ_AT_RESPONSE: u32 = 0x8053;
+    /// Write bytes at an offset. Payload: JSON WriteAtRequest
+    pub const MSG_VFS_WRITE_AT: u32 = 0x8054;
+    /// Write-at response, carrying the file's new size.
+    pub const MSG_VFS_WRITE_AT_RESPONSE: u32 = 0x8055;
+    /// Close a handle. Payload: JSON CloseRequest
+    pub const MSG_VFS_CLOSE: u32 = 0x8056;
+    /// Close response.
+    pub const MSG_VFS_CLOSE_RESPONSE: u32 = 0x8057;
+    /// Attach a shared memory region to a handle, for reads and writes
+    /// that bypass the message size limit. Payload: JSON AttachShmRequest,
+    /// with the region's Memory capability transferred.
+    pub const MSG_VFS_ATTACH_SHM: u32 = 0x8058;
+    /// Attach-shm response, carrying the mapped region size.
+    pub const MSG_VFS_ATTACH_SHM_RESPONSE: u32 = 0x8059;
+}
+
+/// VFS service messages - Watches (0x8060-0x806F).
+///
+/// A watch reports changes to a path, its direct children, or (if
+/// recursive) anything below it. Watches belong to the process that set
+/// them and do not survive a VFS restart.
+pub mod vfs_watch {
+    /// Watch a path. Payload: JSON WatchRequest
+    pub const MSG_VFS_WATCH: u32 = 0x8060;
+    /// Watch response, carrying the watch ID.
+    pub const MSG_VFS_WATCH_RESPONSE: u32 = 0x8061;
+    /// Remove a watch. Payload: JSON UnwatchRequest
+    pub const MSG_VFS_UNWATCH: u32 = 0x8062;
+    /// Unwatch response.
+    pub const MSG_VFS_UNWATCH_RESPONSE: u32 = 0x8063;
+    /// VFS → watcher: a watched path changed. Payload: JSON VfsEvent
+    pub const MSG_VFS_EVENT: u32 = 0x8064;
+}
+
+/// VFS service messages - Mounts (0x8070-0x807F).
+///
+/// Mount a filesystem backend at a path prefix, or remove one. Only Init
+/// and the PermissionManager may mount or unmount; anyone may list. Mounts
+/// do not survive a VFS restart.
+pub mod vfs_mount {
+    /// Mount a backend. Payload: JSON MountRequest
+    pub const MSG_VFS_MOUNT: u32 = 0x8070;
+    /// Mount response.
+    pub const MSG_VFS_MOUNT_RESPONSE: u32 = 0x8071;
+    /// Remove a mount. Payload: JSON UmountRequest
+    pub const MSG_VFS_UMOUNT: u32 = 0x8072;
+    /// Umount response.
+    pub const MSG_VFS_UMOUNT_RESPONSE: u32 = 0x8073;
+    /// List the mount table. Payload: (empty)
+    pub const MSG_VFS_MOUNTS: u32 = 0x8074;
+    /// Mount list response. Payload: JSON MountsResponse
+    pub const MSG_VFS_MOUNTS_RESPONSE: u32 = 0x8075;
+}
+
+/// VFS service messages - Caller Identity (0x8080-0x808F).
+///
+/// Application requests are checked as the session user, the user signed
+/// in on this machine. Only Init, the PermissionManager and the
+/// IdentityService may set it.
+pub mod vfs_caller {
+    /// Set the session user, or clear it. Payload: JSON SetCallerUserRequest
+    pub const MSG_VFS_SET_CALLER_USER: u32 = 0x8080;
+    /// Set caller user response.
+    pub const MSG_VFS_SET_CALLER_USER_RESPONSE: u32 = 0x8081;
+}
+
+/// VFS service messages - Batches (0x8090-0x809F).
+///
+/// A batch carries several file operations in one message and is answered
+/// with one response holding each operation's own response.
+pub mod vfs_batch {
+    /// Run a list of operations. Payload: JSON BatchRequest
+    pub const MSG_VFS_BATCH: u32 = 0x8090;
+    /// Batch response. Payload: JSON BatchResponse
+    pub const MSG_VFS_BATCH_RESPONSE: u32 = 0x8091;
+}
+
+/// VFS service messages - Extended attributes (0x80A0-0x80AF).
+///
+/// Small named values (MIME type, tags, origin URL) stored in a file's or
+/// directory's inode.
+pub mod vfs_xattr {
+    /// Set or remove an attribute. Payload: JSON SetXattrRequest
+    pub const MSG_VFS_SETXATTR: u32 = 0x80A0;
+    /// Set response. Payload: JSON SetXattrResponse
+    pub const MSG_VFS_SETXATTR_RESPONSE: u32 = 0x80A1;
+    /// Get an attribute's value. Payload: JSON GetXattrRequest
+    pub const MSG_VFS_GETXATTR: u32 = 0x80A2;
+    /// Get response. Payload: JSON GetXattrResponse
+    pub const MSG_VFS_GETXATTR_RESPONSE: u32 = 0x80A3;
+    /// List attribute names. Payload: JSON ListXattrRequest
+    pub const MSG_VFS_LISTXATTR: u32 = 0x80A4;
+    /// List response. Payload: JSON ListXattrResponse
+    pub const MSG_VFS_LISTXATTR_RESPONSE: u32 = 0x80A5;
+}
+
+/// VFS service messages - Trash (0x80B0-0x80BF).
+///
+/// Files unlinked with `trash` set are moved to `/home/{user}/.trash`
+/// instead of being deleted; these list, restore and purge them.
+pub mod vfs_trash {
+    /// List a user's trashed files. Payload: JSON TrashListRequest
+    pub const MSG_VFS_TRASH_LIST: u32 = 0x80B0;
+    /// List response. Payload: JSON TrashListResponse
+    pub const MSG_VFS_TRASH_LIST_RESPONSE: u32 = 0x80B1;
+    /// Move a trashed file back. Payload: JSON TrashRestoreRequest
+    pub const MSG_VFS_TRASH_RESTORE: u32 = 0x80B2;
+    /// Restore response. Payload: JSON TrashRestoreResponse
+    pub const MSG_VFS_TRASH_RESTORE_RESPONSE: u32 = 0x80B3;
+    /// Delete trashed files for good. Payload: JSON TrashPurgeRequest
+    pub const MSG_VFS_TRASH_PURGE: u32 = 0x80B4;
+    /// Purge response. Payload: JSON TrashPurgeResponse
+    pub const MSG_VFS_TRASH_PURGE_RESPONSE: u32 = 0x80B5;
+}
+
+/// VFS service messages - Snapshots (0x80C0-0x80CF).
+///
+/// Point-in-time copies of a directory tree under a home directory, which
+/// share content with the live files until either side changes.
+pub mod vfs_snapshot {
+    /// Snapshot a directory tree. Payload: JSON SnapshotCreateRequest
+    pub const MSG_VFS_SNAPSHOT_CREATE: u32 = 0x80C0;
+    /// Create response. Payload: JSON SnapshotCreateResponse
+    pub const MSG_VFS_SNAPSHOT_CREATE_RESPONSE: u32 = 0x80C1;
+    /// List a user's snapshots. Payload: JSON SnapshotListRequest
+    pub const MSG_VFS_SNAPSHOT_LIST: u32 = 0x80C2;
+    /// List response. Payload: JSON SnapshotListResponse
+    pub const MSG_VFS_SNAPSHOT_LIST_RESPONSE: u32 = 0x80C3;
+    /// Roll a tree back to a snapshot. Payload: JSON SnapshotRestoreRequest
+    pub const MSG_VFS_SNAPSHOT_RESTORE: u32 = 0x80C4;
+    /// Restore response. Payload: JSON SnapshotRestoreResponse
+    pub const MSG_VFS_SNAPSHOT_RESTORE_RESPONSE: u32 = 0x80C5;
+    /// Delete a snapshot. Payload: JSON SnapshotDeleteRequest
+    pub const MSG_VFS_SNAPSHOT_DELETE: u32 = 0x80C6;
+    /// Delete response. Payload: JSON SnapshotDeleteResponse
+    pub const MSG_VFS_SNAPSHOT_DELETE_RESPONSE: u32 = 0x80C7;
+}
+
+/// VFS service messages - Integrity (0x80D0-0x80DF).
+///
+/// Hashes are computed by the service as it streams the content from
+/// storage, so a client can check a file of any size without reading it.
+pub mod vfs_hash {
+    /// SHA-256 of a file. Payload: JSON HashRequest
+    pub const MSG_VFS_HASH: u32 = 0x80D0;
+    /// Hash response. Payload: JSON HashResponse
+    pub const MSG_VFS_HASH_RESPONSE: u32 = 0x80D1;
 }
 
 // =============================================================================
@@ -871,7 +1702,9 @@ pub mod vfs_quota {
 /// Time service messages (0x8100-0x810F).
 ///
 /// The Time Service manages time-related settings like time format (12h/24h)
-/// and timezone preferences. Settings are persisted to VFS.
+/// and timezone preferences. Settings are persisted to VFS. It also formats
+/// timestamps for any timezone and locale, with per-process overrides, and
+/// keeps persistent alarms that fire whether or not their app is running.
 pub mod time {
     /// Request current time settings.
     /// Payload: (empty)
@@ -885,6 +1718,532 @@ pub mod time {
     /// Response confirming settings update.
     /// Payload: JSON {"time_format_24h": bool, "timezone": string} or {"error": string}
     pub const MSG_SET_TIME_SETTINGS_RESPONSE: u32 = 0x8103;
+    /// Set the calling process's own timezone/locale override; absent
+    /// fields fall back to the system settings, an empty object clears it.
+    /// Payload: JSON {"timezone": string?, "locale": string?, "time_format_24h": bool?}
+    pub const MSG_TIME_SET_OVERRIDE: u32 = 0x8104;
+    /// Response with the caller's effective settings.
+    /// Payload: JSON {"time_format_24h": bool, "timezone": string, "locale": string} or {"error": string}
+    pub const MSG_TIME_SET_OVERRIDE_RESPONSE: u32 = 0x8105;
+    /// Format a timestamp (default: now) for a timezone and locale; absent
+    /// fields use the caller's override, then the system settings.
+    /// Payload: JSON {"timestamp_ms": u64?, "timezone": string?, "locale": string?,
+    /// "time_format_24h": bool?, "seconds": bool?}
+    pub const MSG_TIME_CONVERT: u32 = 0x8106;
+    /// Response with the local time.
+    /// Payload: JSON-serialized ConvertedTime or {"error": string}
+    pub const MSG_TIME_CONVERT_RESPONSE: u32 = 0x8107;
+    /// Set an alarm at a time (`at_ms`) or after a countdown (`in_ms`).
+    /// Payload: JSON {"at_ms": u64?, "in_ms": u64?, "label": string?,
+    /// "repeat": "daily" | "weekdays" | "weekly" | null}
+    pub const MSG_TIME_SET_ALARM: u32 = 0x8108;
+    /// Response with the armed alarm.
+    /// Payload: JSON-serialized Alarm or {"error": string}
+    pub const MSG_TIME_SET_ALARM_RESPONSE: u32 = 0x8109;
+    /// Cancel one of the caller's alarms.
+    /// Payload: JSON {"id": u32}
+    pub const MSG_TIME_CANCEL_ALARM: u32 = 0x810A;
+    /// Response with the cancelled alarm.
+    /// Payload: JSON-serialized Alarm or {"error": string}
+    pub const MSG_TIME_CANCEL_ALARM_RESPONSE: u32 = 0x810B;
+    /// List the caller's alarms.
+    /// Payload: (empty)
+    pub const MSG_TIME_LIST_ALARMS: u32 = 0x810C;
+    /// Response with the caller's alarms, soonest first.
+    /// Payload: JSON {"alarms": [Alarm]}
+    pub const MSG_TIME_LIST_ALARMS_RESPONSE: u32 = 0x810D;
+    /// An alarm fired (TimeService → every running process of its owner).
+    /// Payload: JSON-serialized Alarm, `fire_at_ms` being when it came due
+    pub const MSG_TIME_ALARM: u32 = 0x810E;
+}
+
+// =============================================================================
+// Update Service (0x8200 - 0x820F)
+// =============================================================================
+
+/// Update service messages (0x8200-0x820F).
+///
+/// The Update Service downloads signed system bundles into the inactive
+/// A/B slot, switches slots on the next boot, and rolls back when the new
+/// version never reports healthy.
+pub mod update {
+    /// Download, verify and stage a bundle into the inactive slot.
+    /// Payload: JSON {"manifest_url": string}
+    pub const MSG_UPDATE_STAGE: u32 = 0x8200;
+    /// Response once the bundle is staged (or failed).
+    /// Payload: JSON {"version": string, "slot": string} or {"error": string}
+    pub const MSG_UPDATE_STAGE_RESPONSE: u32 = 0x8201;
+    /// Query slot state.
+    /// Payload: (empty)
+    pub const MSG_UPDATE_STATUS: u32 = 0x8202;
+    /// Response with slot state.
+    /// Payload: JSON-serialized UpdateStatus
+    pub const MSG_UPDATE_STATUS_RESPONSE: u32 = 0x8203;
+    /// Boot health check passed - commit a trial slot.
+    /// Payload: (empty)
+    pub const MSG_UPDATE_HEALTH_OK: u32 = 0x8204;
+    /// Response to health confirmation.
+    /// Payload: JSON-serialized UpdateStatus or {"error": string}
+    pub const MSG_UPDATE_HEALTH_OK_RESPONSE: u32 = 0x8205;
+    /// Boot health check failed - roll back a trial slot.
+    /// Payload: JSON {"reason": string}
+    pub const MSG_UPDATE_HEALTH_FAILED: u32 = 0x8206;
+    /// Response to health failure report.
+    /// Payload: JSON-serialized UpdateStatus or {"error": string}
+    pub const MSG_UPDATE_HEALTH_FAILED_RESPONSE: u32 = 0x8207;
+}
+
+// =============================================================================
+// Feature Flag Service (0x8300 - 0x830F)
+// =============================================================================
+
+/// Feature flag service messages (0x8300-0x830F).
+///
+/// The Feature Flag Service owns `/system/config/flags.json` and resolves system-wide
+/// values and per-user overrides into a snapshot of enabled flags.
+pub mod flags {
+    /// Resolve flags for a user (or system-wide).
+    /// Payload: JSON {"user_id": string (32 hex chars)?} or (empty)
+    pub const MSG_FLAGS_GET: u32 = 0x8300;
+    /// Response with resolved flags.
+    /// Payload: JSON-serialized FeatureFlags or {"error": string}
+    pub const MSG_FLAGS_GET_RESPONSE: u32 = 0x8301;
+    /// Set or clear a flag value.
+    /// Payload: JSON {"name": string, "enabled": bool | null, "user_id": string?}
+    pub const MSG_FLAGS_SET: u32 = 0x8302;
+    /// Response after the change is persisted.
+    /// Payload: JSON-serialized FeatureFlags (for the affected scope) or {"error": string}
+    pub const MSG_FLAGS_SET_RESPONSE: u32 = 0x8303;
+    /// List the raw stored values and built-in flag definitions.
+    /// Payload: (empty)
+    pub const MSG_FLAGS_LIST: u32 = 0x8304;
+    /// Response with stored values.
+    /// Payload: JSON {"store": FlagStore, "known": [{"name", "default", "description"}]}
+    pub const MSG_FLAGS_LIST_RESPONSE: u32 = 0x8305;
+}
+
+// =============================================================================
+// Desktop Automation (0x8400 - 0x840F)
+// =============================================================================
+
+/// Desktop automation messages (0x8400-0x840F).
+///
+/// Processes submit scripts with the `DESKTOP:SCRIPT:` debug message; the
+/// supervisor runs them against the desktop engine (when the
+/// `desktop.automation` flag is on) and replies with the report.
+pub mod desktop {
+    /// Script report delivered to the submitting process.
+    /// Payload: JSON ScriptReport {"outputs": [...], "failure": {"step", "message"}?, "now_ms"}
+    pub const MSG_DESKTOP_SCRIPT_RESULT: u32 = 0x8400;
+}
+
+// =============================================================================
+// Speech Service (0x8500 - 0x850F)
+// =============================================================================
+
+/// Speech service messages (0x8500-0x850F).
+///
+/// The Speech Service queues text for the screen reader output channel and
+/// hands one utterance at a time to the supervisor, which speaks it with the
+/// browser's `speechSynthesis`.
+pub mod speech {
+    /// Queue text to be spoken.
+    /// Payload: JSON {"text": string, "priority": "low"|"normal"|"high"?, "interrupt": bool?}
+    pub const MSG_SPEAK: u32 = 0x8500;
+    /// Response once the utterance is queued.
+    /// Payload: JSON {"id": u32} or {"muted": true} or {"error": string}
+    pub const MSG_SPEAK_RESPONSE: u32 = 0x8501;
+    /// Cancel the caller's current and queued utterances.
+    /// Payload: (empty)
+    pub const MSG_SPEECH_STOP: u32 = 0x8502;
+    /// Response with the number of utterances cancelled.
+    /// Payload: JSON {"cancelled": u32}
+    pub const MSG_SPEECH_STOP_RESPONSE: u32 = 0x8503;
+    /// Mute or unmute an app's speech (system processes only).
+    /// Payload: JSON {"pid": u32, "muted": bool}
+    pub const MSG_SPEECH_SET_MUTE: u32 = 0x8504;
+    /// Response with the muted PIDs.
+    /// Payload: JSON {"muted": [u32]} or {"error": string}
+    pub const MSG_SPEECH_SET_MUTE_RESPONSE: u32 = 0x8505;
+    /// Supervisor → Speech: the current utterance finished (or failed).
+    /// Payload: [utterance_id: u32]
+    pub const MSG_SPEECH_DONE: u32 = 0x8506;
+}
+
+// =============================================================================
+// Event Bus Service (0x8600 - 0x860F)
+// =============================================================================
+
+/// Event bus messages (0x8600-0x860F).
+///
+/// The Event Bus Service routes published events to every process subscribed
+/// to a matching topic pattern, and replays retained events to new
+/// subscribers.
+pub mod events {
+    /// Publish an event.
+    /// Payload: JSON {"topic": string, "payload": any, "retain": bool?}
+    pub const MSG_EVENT_PUBLISH: u32 = 0x8600;
+    /// Response with the number of subscribers the event was sent to.
+    /// Payload: JSON {"delivered": u32} or {"error": string}
+    pub const MSG_EVENT_PUBLISH_RESPONSE: u32 = 0x8601;
+    /// Subscribe to a topic pattern (`+` one level, trailing `#` any levels).
+    /// Payload: JSON {"pattern": string}
+    pub const MSG_EVENT_SUBSCRIBE: u32 = 0x8602;
+    /// Response with the subscription ID; matching retained events follow.
+    /// Payload: JSON {"id": u32} or {"error": string}
+    pub const MSG_EVENT_SUBSCRIBE_RESPONSE: u32 = 0x8603;
+    /// Drop one subscription, or all of the caller's when no ID is given.
+    /// Payload: JSON {"id": u32?}
+    pub const MSG_EVENT_UNSUBSCRIBE: u32 = 0x8604;
+    /// Response with the number of subscriptions removed.
+    /// Payload: JSON {"removed": u32}
+    pub const MSG_EVENT_UNSUBSCRIBE_RESPONSE: u32 = 0x8605;
+    /// Event → subscriber.
+    /// Payload: JSON {"subscription": u32, "topic": string, "payload": any,
+    /// "publisher": u32, "retained": bool}
+    pub const MSG_EVENT: u32 = 0x8606;
+    /// Set the access rule for a topic pattern (system processes only).
+    /// Payload: JSON {"pattern": string, "publish": access, "subscribe": access}
+    /// where access is "anyone", "system" or {"pids": [u32]}
+    pub const MSG_EVENT_SET_ACL: u32 = 0x8608;
+    /// Response with the current rules.
+    /// Payload: JSON {"rules": [rule]} or {"error": string}
+    pub const MSG_EVENT_SET_ACL_RESPONSE: u32 = 0x8609;
+}
+
+// =============================================================================
+// Calendar Service (0x8700 - 0x870F)
+// =============================================================================
+
+/// Calendar service messages (0x8700-0x870F).
+///
+/// The Calendar Service keeps the events shared by the Calendar app and
+/// desktop widgets, expands recurring events for a time range and arms
+/// their reminders as TimeService alarms.
+pub mod calendar {
+    /// Add an event.
+    /// Payload: JSON {"title": string, "start_ms": u64, "end_ms": u64?, ...}
+    pub const MSG_CAL_ADD: u32 = 0x8700;
+    /// Response with the stored event.
+    /// Payload: JSON Event or {"error": string}
+    pub const MSG_CAL_ADD_RESPONSE: u32 = 0x8701;
+    /// Replace an event's fields.
+    /// Payload: JSON {"id": u32, ...fields as for MSG_CAL_ADD}
+    pub const MSG_CAL_UPDATE: u32 = 0x8702;
+    /// Response with the updated event.
+    /// Payload: JSON Event or {"error": string}
+    pub const MSG_CAL_UPDATE_RESPONSE: u32 = 0x8703;
+    /// Delete an event (every occurrence).
+    /// Payload: JSON {"id": u32}
+    pub const MSG_CAL_DELETE: u32 = 0x8704;
+    /// Response with the deleted event.
+    /// Payload: JSON Event or {"error": string}
+    pub const MSG_CAL_DELETE_RESPONSE: u32 = 0x8705;
+    /// Query the occurrences overlapping a time range.
+    /// Payload: JSON {"from_ms": u64, "to_ms": u64, "calendar": string?}
+    pub const MSG_CAL_QUERY: u32 = 0x8706;
+    /// Response with the occurrences, soonest first.
+    /// Payload: JSON {"occurrences": [Occurrence], "truncated": bool} or {"error": string}
+    pub const MSG_CAL_QUERY_RESPONSE: u32 = 0x8707;
+    /// Import the events of an iCalendar (ICS) file or text.
+    /// Payload: JSON {"path": string} or {"ics": string}, plus {"calendar": string?}
+    pub const MSG_CAL_IMPORT: u32 = 0x8708;
+    /// Response with import counts.
+    /// Payload: JSON {"imported": u32, "updated": u32, "skipped": u32,
+    /// "warnings": [string]} or {"error": string}
+    pub const MSG_CAL_IMPORT_RESPONSE: u32 = 0x8709;
+}
+
+// =============================================================================
+// Contacts Service (0x8800 - 0x880F)
+// =============================================================================
+
+/// Contacts (address book) service messages (0x8800-0x880F).
+///
+/// The Contacts Service keeps the people a user shares with and messages:
+/// their names, public keys and the endpoints their machines sync through.
+pub mod contacts {
+    /// Add a contact.
+    /// Payload: JSON {"name": string, "identity_key": hex?, "encryption_key": hex?,
+    /// "endpoints": [Endpoint]?, "notes": string?}
+    pub const MSG_CONTACT_ADD: u32 = 0x8800;
+    /// Response with the stored contact.
+    /// Payload: JSON Contact or {"error": string}
+    pub const MSG_CONTACT_ADD_RESPONSE: u32 = 0x8801;
+    /// Replace a contact's fields. A changed identity key clears verification.
+    /// Payload: JSON {"id": u32, ...fields as for MSG_CONTACT_ADD}
+    pub const MSG_CONTACT_UPDATE: u32 = 0x8802;
+    /// Response with the updated contact.
+    /// Payload: JSON Contact or {"error": string}
+    pub const MSG_CONTACT_UPDATE_RESPONSE: u32 = 0x8803;
+    /// Delete a contact.
+    /// Payload: JSON {"id": u32}
+    pub const MSG_CONTACT_DELETE: u32 = 0x8804;
+    /// Response with the deleted contact.
+    /// Payload: JSON Contact or {"error": string}
+    pub const MSG_CONTACT_DELETE_RESPONSE: u32 = 0x8805;
+    /// List contacts, optionally only those whose name contains a search.
+    /// Payload: JSON {"search": string?}
+    pub const MSG_CONTACT_LIST: u32 = 0x8806;
+    /// Response with the contacts, by name.
+    /// Payload: JSON {"contacts": [Contact]} or {"error": string}
+    pub const MSG_CONTACT_LIST_RESPONSE: u32 = 0x8807;
+    /// Mark a contact's identity key verified, given the fingerprint the
+    /// contact read out over another channel.
+    /// Payload: JSON {"id": u32, "fingerprint": string}
+    pub const MSG_CONTACT_VERIFY: u32 = 0x8808;
+    /// Response with the verified contact.
+    /// Payload: JSON Contact or {"error": string}
+    pub const MSG_CONTACT_VERIFY_RESPONSE: u32 = 0x8809;
+    /// Export contacts as cards.
+    /// Payload: JSON {"ids": [u32]?} (all contacts if absent)
+    pub const MSG_CONTACT_EXPORT: u32 = 0x880A;
+    /// Response with the exported cards.
+    /// Payload: JSON {"version": u32, "cards": [ContactCard]} or {"error": string}
+    pub const MSG_CONTACT_EXPORT_RESPONSE: u32 = 0x880B;
+    /// Import cards, from a VFS file or given inline.
+    /// Payload: JSON {"path": string} or {"cards": [ContactCard]}
+    pub const MSG_CONTACT_IMPORT: u32 = 0x880C;
+    /// Response with import counts.
+    /// Payload: JSON {"imported": u32, "updated": u32, "skipped": u32,
+    /// "warnings": [string]} or {"error": string}
+    pub const MSG_CONTACT_IMPORT_RESPONSE: u32 = 0x880D;
+    /// The card this user shares with contacts, with keys from IdentityService.
+    /// Payload: JSON {"user_id": hex, "name": string, "endpoints": [Endpoint]?}
+    pub const MSG_CONTACT_MY_CARD: u32 = 0x880E;
+    /// Response with the card and its identity key fingerprint.
+    /// Payload: JSON ContactCard or {"error": string}
+    pub const MSG_CONTACT_MY_CARD_RESPONSE: u32 = 0x880F;
+}
+
+// =============================================================================
+// Messaging Service (0x8900 - 0x890F)
+// =============================================================================
+
+/// Messaging service messages (0x8900-0x890F).
+///
+/// The Messaging Service exchanges end-to-end encrypted messages with a
+/// user's other enrolled machines and address-book contacts through relay
+/// mailboxes, queueing them while offline. New messages and status changes
+/// are published on the event bus as `messaging/received` and
+/// `messaging/status`.
+pub mod messaging {
+    /// Open a user's mailbox: load their machine keys and messages.
+    /// Payload: JSON {"user_id": hex, "relays": [string]?}
+    pub const MSG_MESSAGING_OPEN: u32 = 0x8900;
+    /// Response with the mailbox key and relays.
+    /// Payload: JSON {"key": hex, "fingerprint": string, "machines": u32,
+    /// "relays": [string]} or {"error": string}
+    pub const MSG_MESSAGING_OPEN_RESPONSE: u32 = 0x8901;
+    /// Send a message to a contact or to the user's other machines.
+    /// Payload: JSON {"to": {"contact": u32} | "machines", "body": string}
+    pub const MSG_MESSAGING_SEND: u32 = 0x8902;
+    /// Response with the queued message.
+    /// Payload: JSON MessageRecord or {"error": string}
+    pub const MSG_MESSAGING_SEND_RESPONSE: u32 = 0x8903;
+    /// List the latest messages, optionally with one peer.
+    /// Payload: JSON {"peer": {"contact": u32} | "machines"?, "limit": u32?}
+    pub const MSG_MESSAGING_LIST: u32 = 0x8904;
+    /// Response with the messages, oldest first.
+    /// Payload: JSON {"messages": [MessageRecord]} or {"error": string}
+    pub const MSG_MESSAGING_LIST_RESPONSE: u32 = 0x8905;
+    /// Mark received messages read, sending read receipts.
+    /// Payload: JSON {"ids": [string]}
+    pub const MSG_MESSAGING_MARK_READ: u32 = 0x8906;
+    /// Response with the number of messages marked.
+    /// Payload: JSON {"marked": u32} or {"error": string}
+    pub const MSG_MESSAGING_MARK_READ_RESPONSE: u32 = 0x8907;
+    /// Check the relays and retry the outbox now.
+    /// Payload: (empty)
+    pub const MSG_MESSAGING_SYNC: u32 = 0x8908;
+    /// Response with the number of envelopes still queued.
+    /// Payload: JSON {"queued": u32} or {"error": string}
+    pub const MSG_MESSAGING_SYNC_RESPONSE: u32 = 0x8909;
+    /// Clipboard → Messaging: seal a clipboard entry for the user's other
+    /// machines. Only the ClipboardService may send it.
+    /// Payload: JSON {"text": string, "copied_at_ms": u64}
+    pub const MSG_MESSAGING_SEND_CLIPBOARD: u32 = 0x890A;
+    /// Response with the number of machines the entry was sealed for.
+    /// Payload: JSON {"machines": u32} or {"error": string}
+    pub const MSG_MESSAGING_SEND_CLIPBOARD_RESPONSE: u32 = 0x890B;
+}
+
+// =============================================================================
+// Clipboard Service (0x8E00 - 0x8E0F)
+// =============================================================================
+
+/// Clipboard messages (0x8E00-0x8E0F).
+///
+/// The Clipboard Service owns the clipboard and a short history of what was
+/// copied. With sync turned on in Settings, entries are sealed for the
+/// user's other enrolled machines through the Messaging Service; sync is
+/// off by default and set per machine.
+pub mod clipboard {
+    /// Copy text to the clipboard.
+    /// Payload: JSON {"text": string, "source": string?, "sensitive": bool?}
+    pub const MSG_CLIPBOARD_COPY: u32 = 0x8E00;
+    /// Response with the entry and whether it is being synced.
+    /// Payload: JSON {"id": u64, "synced": bool, "excluded": string?} or {"error": string}
+    pub const MSG_CLIPBOARD_COPY_RESPONSE: u32 = 0x8E01;
+    /// Get the current clipboard entry.
+    /// Payload: (empty)
+    pub const MSG_CLIPBOARD_PASTE: u32 = 0x8E02;
+    /// Response with the entry.
+    /// Payload: JSON {"entry": ClipboardEntry | null}
+    pub const MSG_CLIPBOARD_PASTE_RESPONSE: u32 = 0x8E03;
+    /// List recent entries, newest first.
+    /// Payload: JSON {"limit": u32?}
+    pub const MSG_CLIPBOARD_HISTORY: u32 = 0x8E04;
+    /// Response with the entries.
+    /// Payload: JSON {"entries": [ClipboardEntry]}
+    pub const MSG_CLIPBOARD_HISTORY_RESPONSE: u32 = 0x8E05;
+    /// Get this machine's sync settings.
+    /// Payload: (empty)
+    pub const MSG_CLIPBOARD_GET_SYNC: u32 = 0x8E06;
+    /// Response with the settings.
+    /// Payload: JSON SyncSettings or {"error": string}
+    pub const MSG_CLIPBOARD_GET_SYNC_RESPONSE: u32 = 0x8E07;
+    /// Change this machine's sync settings (Settings); absent fields are kept.
+    /// Payload: JSON {"enabled": bool?, "max_bytes": u32?, "excluded_sources": [string]?}
+    pub const MSG_CLIPBOARD_SET_SYNC: u32 = 0x8E08;
+    /// Response after the change is persisted.
+    /// Payload: JSON SyncSettings or {"error": string}
+    pub const MSG_CLIPBOARD_SET_SYNC_RESPONSE: u32 = 0x8E09;
+    /// Messaging → Clipboard: an entry copied on another of the user's machines.
+    /// Payload: JSON {"text": string, "copied_at_ms": u64}
+    pub const MSG_CLIPBOARD_REMOTE: u32 = 0x8E0A;
+}
+
+// =============================================================================
+// Print Service (0x8A00 - 0x8A0F)
+// =============================================================================
+
+/// Print service messages (0x8A00-0x8A0F).
+///
+/// The Print Service takes print jobs from apps as vector pages or HTML and
+/// either hands them to the supervisor's print dialog, one at a time, or
+/// renders vector pages to a PDF in the user's Documents directory. Job
+/// status changes are published on the event bus as `print/job`.
+pub mod print {
+    /// Submit a print job.
+    /// Payload: JSON {"title": string, "target": "dialog"|"pdf"?, "user_id": hex?,
+    /// "document": {"kind": "pages", ...} | {"kind": "html", "html": string}}
+    pub const MSG_PRINT_SUBMIT: u32 = 0x8A00;
+    /// Response with the new job.
+    /// Payload: JSON PrintJob or {"error": string}
+    pub const MSG_PRINT_SUBMIT_RESPONSE: u32 = 0x8A01;
+    /// Get the caller's jobs, or one of them.
+    /// Payload: JSON {"id": u32?}
+    pub const MSG_PRINT_STATUS: u32 = 0x8A02;
+    /// Response with the jobs, oldest first.
+    /// Payload: JSON {"jobs": [PrintJob]} or {"error": string}
+    pub const MSG_PRINT_STATUS_RESPONSE: u32 = 0x8A03;
+    /// Cancel one of the caller's jobs that hasn't started.
+    /// Payload: JSON {"id": u32}
+    pub const MSG_PRINT_CANCEL: u32 = 0x8A04;
+    /// Response with the cancelled job.
+    /// Payload: JSON PrintJob or {"error": string}
+    pub const MSG_PRINT_CANCEL_RESPONSE: u32 = 0x8A05;
+    /// Supervisor → Print: the print dialog for a job closed.
+    /// Payload: JSON {"id": u32, "outcome": "printed"|"cancelled"|"failed", "error": string?}
+    pub const MSG_PRINT_DIALOG_DONE: u32 = 0x8A06;
+}
+
+// =============================================================================
+// Thumbnail Service (0x8B00 - 0x8B0F)
+// =============================================================================
+
+/// Thumbnail service messages (0x8B00-0x8B0F).
+///
+/// The Thumbnail Service makes small previews of image files for the file
+/// manager and image viewer. Images are decoded and scaled by the
+/// supervisor; the results are cached under `/var/cache/thumbnails`, keyed
+/// by the SHA-256 of the source content and the thumbnail size.
+pub mod thumbnail {
+    /// Get a thumbnail of an image file, making it if it isn't cached.
+    /// Payload: JSON {"path": string, "size": u32?}
+    pub const MSG_THUMBNAIL_GET: u32 = 0x8B00;
+    /// Response with where the thumbnail is.
+    /// Payload: JSON {"source", "path", "hash", "size", "width", "height", "mime", "cached"}
+    /// or {"error": string}
+    pub const MSG_THUMBNAIL_GET_RESPONSE: u32 = 0x8B01;
+    /// Supervisor → Thumbnail: a decode finished.
+    /// Payload: [id: u32, ok: u8, then width: u16, height: u16, image: [u8] if ok
+    /// or error: UTF-8 if not]
+    pub const MSG_THUMBNAIL_DECODED: u32 = 0x8B02;
+}
+
+// =============================================================================
+// Archive Service (0x8C00 - 0x8C0F)
+// =============================================================================
+
+/// Archive service messages (0x8C00-0x8C0F).
+///
+/// The Archive Service creates and extracts zip and tar archives in the
+/// VFS, streaming them through file handles so no archive is held in
+/// memory whole. Jobs run in the background; their progress is published
+/// on the event bus under `archive/progress`.
+pub mod archive {
+    /// Archive a file or directory.
+    /// Payload: JSON {"source": string, "archive": string, "format": "zip"|"tar"?,
+    /// "user_id": hex}
+    pub const MSG_ARCHIVE_CREATE: u32 = 0x8C00;
+    /// Response with the started job.
+    /// Payload: JSON ArchiveJob or {"error": string}
+    pub const MSG_ARCHIVE_CREATE_RESPONSE: u32 = 0x8C01;
+    /// Extract an archive into a new directory.
+    /// Payload: JSON {"archive": string, "dest": string, "user_id": hex}
+    pub const MSG_ARCHIVE_EXTRACT: u32 = 0x8C02;
+    /// Response with the started job.
+    /// Payload: JSON ArchiveJob or {"error": string}
+    pub const MSG_ARCHIVE_EXTRACT_RESPONSE: u32 = 0x8C03;
+    /// Get the caller's jobs, or one of them.
+    /// Payload: JSON {"id": u32?}
+    pub const MSG_ARCHIVE_STATUS: u32 = 0x8C04;
+    /// Response with the jobs, oldest first.
+    /// Payload: JSON {"jobs": [ArchiveJob]} or {"error": string}
+    pub const MSG_ARCHIVE_STATUS_RESPONSE: u32 = 0x8C05;
+}
+
+// =============================================================================
+// Command Service (0x8D00 - 0x8D0F)
+// =============================================================================
+
+/// Command palette messages (0x8D00-0x8D0F).
+///
+/// The Command Service keeps the commands apps declare in their manifest or
+/// register at runtime, fuzzy-matches palette queries across all of them and
+/// hands an invoked command to the app that owns it.
+pub mod commands {
+    /// Register commands for the caller, replacing any with the same ID.
+    /// Payload: JSON {"app": string?, "commands": [{"id": string, "title": string,
+    /// "keywords": [string]?}]}
+    pub const MSG_COMMAND_REGISTER: u32 = 0x8D00;
+    /// Response with the number of commands registered.
+    /// Payload: JSON {"registered": u32} or {"error": string}
+    pub const MSG_COMMAND_REGISTER_RESPONSE: u32 = 0x8D01;
+    /// Drop one of the caller's commands, or all of them when no ID is given.
+    /// Payload: JSON {"id": string?}
+    pub const MSG_COMMAND_UNREGISTER: u32 = 0x8D02;
+    /// Response with the number of commands removed.
+    /// Payload: JSON {"removed": u32}
+    pub const MSG_COMMAND_UNREGISTER_RESPONSE: u32 = 0x8D03;
+    /// Fuzzy-match registered commands; an empty query lists them all.
+    /// Payload: JSON {"query": string, "limit": u32?}
+    pub const MSG_COMMAND_QUERY: u32 = 0x8D04;
+    /// Response with the matches, best first.
+    /// Payload: JSON {"results": [{"pid", "app", "id", "title", "score"}]}
+    /// or {"error": string}
+    pub const MSG_COMMAND_QUERY_RESPONSE: u32 = 0x8D05;
+    /// Invoke a command (system processes only).
+    /// Payload: JSON {"pid": u32, "id": string, "args": any?}
+    pub const MSG_COMMAND_INVOKE: u32 = 0x8D06;
+    /// Response once the command is handed to its app.
+    /// Payload: JSON {"invoked": true} or {"error": string}
+    pub const MSG_COMMAND_INVOKE_RESPONSE: u32 = 0x8D07;
+    /// Invoked command → owning app.
+    /// Payload: JSON {"id": string, "args": any}
+    pub const MSG_COMMAND: u32 = 0x8D08;
 }
 
 // =============================================================================
@@ -905,6 +2264,37 @@ pub mod net {
     /// Network result delivered via IPC (async callback).
     /// Payload format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
     pub const MSG_NET_RESULT: u32 = 0x9002;
+    /// Get the network policy.
+    /// Payload: (empty)
+    pub const MSG_NET_POLICY_GET: u32 = 0x9010;
+    /// Response with the current policy.
+    /// Payload: JSON-serialized NetworkPolicy
+    pub const MSG_NET_POLICY_GET_RESPONSE: u32 = 0x9011;
+    /// Change the network policy (Settings); absent fields are kept.
+    /// Payload: JSON {"offline": bool?, "allow": [string]?, "deny": [string]?}
+    pub const MSG_NET_POLICY_SET: u32 = 0x9012;
+    /// Response after the change is persisted.
+    /// Payload: JSON-serialized NetworkPolicy or {"error": string}
+    pub const MSG_NET_POLICY_SET_RESPONSE: u32 = 0x9013;
+    /// Get per-process traffic counters.
+    /// Payload: (empty)
+    pub const MSG_NET_STATS: u32 = 0x9014;
+    /// Response with the counters.
+    /// Payload: JSON-serialized NetworkStats
+    pub const MSG_NET_STATS_RESPONSE: u32 = 0x9015;
+    /// Drop cached responses for one URL, or all of them.
+    /// Payload: JSON {"url": string?}
+    pub const MSG_NET_CACHE_PURGE: u32 = 0x9016;
+    /// Response once the entries are dropped.
+    /// Payload: JSON-serialized CacheStats or {"error": string}
+    pub const MSG_NET_CACHE_PURGE_RESPONSE: u32 = 0x9017;
+    /// Result of a durable job interrupted by a reload, sent to the
+    /// process with the owner's name until acknowledged.
+    /// Payload: JSON {"job": string, "response": HttpResponse}
+    pub const MSG_NET_JOB_RESULT: u32 = 0x9018;
+    /// Acknowledge a durable job result, deleting the job.
+    /// Payload: JSON {"job": string}
+    pub const MSG_NET_JOB_ACK: u32 = 0x9019;
 }
 
 // =============================================================================
@@ -967,7 +2357,10 @@ pub mod keystore_svc {
 /// provides compile-time safety and consistency.
 pub mod debug {
     // === Init Protocol ===
-    /// Init spawn request: "INIT:SPAWN:{service_name}"
+    /// Init spawn request: "INIT:SPAWN:{service_name}", or
+    /// "INIT:SPAWN:{service_name}:{requester_pid}" from Init on behalf of
+    /// another process. The new process joins the requester's process
+    /// group (the sender's, if that is not Init).
     pub const INIT_SPAWN: &str = "INIT:SPAWN:";
     /// Init grant capability: "INIT:GRANT:{details}"
     pub const INIT_GRANT: &str = "INIT:GRANT:";
@@ -981,6 +2374,10 @@ pub mod debug {
     pub const INIT_PERM_RESPONSE: &str = "INIT:PERM_RESPONSE:";
     /// Init permission list: "INIT:PERM_LIST:{details}"
     pub const INIT_PERM_LIST: &str = "INIT:PERM_LIST:";
+    /// Crash of a service or app:
+    /// "INIT:CRASH:{pid}:{exit_code}:{recent_crashes}:{failed}:{name}"
+    /// failed=1 when the crash completed a crash loop and it is not restarted
+    pub const INIT_CRASH: &str = "INIT:CRASH:";
     /// Generic init message prefix
     pub const INIT_PREFIX: &str = "INIT:";
 
@@ -989,8 +2386,36 @@ pub mod debug {
     pub const SERVICE_RESPONSE: &str = "SERVICE:RESPONSE:";
     /// VFS service response: "VFS:RESPONSE:{hex_data}"
     pub const VFS_RESPONSE: &str = "VFS:RESPONSE:";
+    /// VFS pending operation counters for metrics: "VFS:STATS:{hex_json}"
+    pub const VFS_STATS: &str = "VFS:STATS:";
     /// Keystore service response: "KEYSTORE:RESPONSE:{to_pid}:{tag_hex}:{hex_data}"
     pub const KEYSTORE_RESPONSE: &str = "KEYSTORE:RESPONSE:";
+    /// Feature flag snapshot broadcast: "FLAGS:SNAPSHOT:{hex_json}"
+    pub const FLAGS_SNAPSHOT: &str = "FLAGS:SNAPSHOT:";
+    /// Network service response: "NET:RESPONSE:{to_pid}:{tag_hex}:{hex_data}"
+    pub const NET_RESPONSE: &str = "NET:RESPONSE:";
+    /// Network traffic counters for metrics: "NET:STATS:{hex_json}"
+    pub const NET_STATS: &str = "NET:STATS:";
+    /// Desktop automation script: "DESKTOP:SCRIPT:{hex_json}"
+    pub const DESKTOP_SCRIPT: &str = "DESKTOP:SCRIPT:";
+    /// Utterance for the supervisor to speak: "SPEECH:SPEAK:{hex_json}"
+    pub const SPEECH_SPEAK: &str = "SPEECH:SPEAK:";
+    /// Stop the utterance being spoken: "SPEECH:CANCEL"
+    pub const SPEECH_CANCEL: &str = "SPEECH:CANCEL";
+    /// Event for a subscriber: "EVENT:DELIVER:{to_pid}:{tag_hex}:{hex_json}"
+    pub const EVENT_DELIVER: &str = "EVENT:DELIVER:";
+    /// Commands an app declares in its manifest: "COMMAND:REGISTER:{hex_json}"
+    pub const COMMAND_REGISTER: &str = "COMMAND:REGISTER:";
+    /// Invoked command for its app: "COMMAND:DELIVER:{to_pid}:{tag_hex}:{hex_json}"
+    pub const COMMAND_DELIVER: &str = "COMMAND:DELIVER:";
+    /// Desktop notification to show: "NOTIFY:SHOW:{hex_json}"
+    pub const NOTIFY_SHOW: &str = "NOTIFY:SHOW:";
+    /// Print job for the print dialog: "PRINT:DIALOG:{hex_json}"
+    pub const PRINT_DIALOG: &str = "PRINT:DIALOG:";
+    /// Image to decode and scale: "THUMBNAIL:DECODE:{id}:{size}:{hex_data}"
+    pub const THUMBNAIL_DECODE: &str = "THUMBNAIL:DECODE:";
+    /// Capability request the user must approve: "PERMSVC:PROMPT:{hex_json}"
+    pub const PERMSVC_PROMPT: &str = "PERMSVC:PROMPT:";
 
     // === Spawn Protocol ===
     /// Spawn response: "SPAWN:RESPONSE:{hex_data}"
@@ -1013,6 +2438,10 @@ pub mod debug {
 pub mod slots {
     /// Init's endpoint slot (every process gets this at spawn).
     pub const INIT_ENDPOINT_SLOT: u32 = 2;
+
+    // Every process also gets a lookup-reply endpoint at spawn, but its slot
+    // depends on which services were running. It is the only receive-only
+    // endpoint capability a process holds; see `zos_process::lookup_reply_slot`.
 }
 
 // =============================================================================
@@ -1044,6 +2473,67 @@ pub mod pid {
     pub const KEYSTORE_SERVICE: u32 = 7;
 }
 
+// =============================================================================
+// Well-Known Service Names
+// =============================================================================
+
+/// Names services recognize each other by.
+///
+/// Only the supervisor and Init have fixed PIDs: boot services spawn as
+/// their dependencies become ready, and a restarted service comes back
+/// under a fresh PID. A caller is identified by the name it runs under in
+/// the kernel's process table instead, the name of the binary Init or the
+/// supervisor spawned for it.
+pub mod services {
+    /// PermissionService - capability authority
+    pub const PERMISSION: &str = "permission";
+    /// IdentityService - user/session management
+    pub const IDENTITY: &str = "identity";
+    /// The desktop shell, trusted to act for the signed-in user
+    pub const DESKTOP: &str = "desktop";
+
+    /// Services Init spawns at boot. They act for every user and are
+    /// checked as system processes.
+    pub const SYSTEM: &[&str] = &[
+        "permission",
+        "vfs",
+        "keystore",
+        "identity",
+        "time",
+        "update",
+        "flags",
+        "speech",
+        "events",
+        "network",
+        "calendar",
+        "contacts",
+        "messaging",
+        "clipboard",
+        "print",
+        "thumbnail",
+        "archive",
+        "commands",
+    ];
+}
+
+// =============================================================================
+// Scheduling Priority Classes
+// =============================================================================
+
+/// Scheduling priority classes for `SYS_SET_PRIORITY`.
+///
+/// Lower values run first: whenever a process of a higher class is ready,
+/// the scheduler runs it ahead of lower classes. Init and the supervisor
+/// start as system services, every other process as interactive.
+pub mod priority {
+    /// Core services the rest of the system waits on (Init, VFS, ...)
+    pub const SYSTEM_SERVICE: u8 = 0;
+    /// Apps a user is looking at (desktop, terminal, ...)
+    pub const INTERACTIVE: u8 = 1;
+    /// Work nobody is waiting on (indexing, sync, ...)
+    pub const BACKGROUND: u8 = 2;
+}
+
 // =============================================================================
 // Syscall Error Codes (for SYS_LOAD_BINARY, SYS_SPAWN_PROCESS)
 // =============================================================================
@@ -1065,6 +2555,18 @@ pub mod syscall_error {
     pub const INVALID_ARGUMENT: i32 = -5;
     /// Process spawn failed
     pub const SPAWN_FAILED: i32 = -6;
+    /// Transient failure, retrying may succeed (e.g., injected by fault injection)
+    pub const TRANSIENT: i32 = -7;
+    /// Endpoint queue full; the sender is in line and keeps its place by retrying
+    pub const WOULD_BLOCK: i32 = -8;
+    /// No reply before the call's timeout or deadline
+    pub const TIMED_OUT: i32 = -9;
+    /// The process that had to reply died first
+    pub const PEER_DIED: i32 = -10;
+    /// The caller already has a call in progress
+    pub const BUSY: i32 = -11;
+    /// A per-process or system-wide resource limit was reached
+    pub const LIMIT_EXCEEDED: i32 = -12;
 }
 
 #[cfg(test)]
@@ -1091,29 +2593,192 @@ mod tests {
     fn test_message_ranges() {
         // Init service in 0x1000-0x100F
         const { assert!(init::MSG_REGISTER_SERVICE >= 0x1000) };
-        const { assert!(init::MSG_VFS_RESPONSE_CAP_GRANTED <= 0x100F) };
+        const { assert!(init::MSG_PROCESS_OOM <= 0x100F) };
 
         // PM in 0x2010-0x201F
         const { assert!(pm::MSG_REQUEST_CAPABILITY >= 0x2010) };
         const { assert!(pm::MSG_CAPS_LIST_RESPONSE <= 0x201F) };
 
+        // Kernel notifications in 0x3000-0x30FF
+        const { assert!(kernel::MSG_CAP_REVOKED >= 0x3000) };
+        const { assert!(kernel::MSG_TIMER_FIRED <= 0x30FF) };
+
         // Identity in 0x7000-0x70FF
         const { assert!(identity_user::MSG_CREATE_USER >= 0x7000) };
         const { assert!(identity_machine::MSG_ROTATE_MACHINE_KEY_RESPONSE <= 0x70FF) };
+        const { assert!(identity_enroll::MSG_REQUEST_ENROLLMENT >= 0x7070) };
+        const { assert!(identity_enroll::MSG_COMPLETE_ENROLLMENT_RESPONSE <= 0x707F) };
+        const { assert!(identity_query::MSG_GET_KEY_FINGERPRINT_RESPONSE <= 0x703F) };
 
         // VFS in 0x8000-0x80FF
         const { assert!(vfs_dir::MSG_VFS_MKDIR >= 0x8000) };
-        const { assert!(vfs_quota::MSG_VFS_GET_QUOTA_RESPONSE <= 0x80FF) };
+        const { assert!(vfs_signed::MSG_VFS_REGISTER_SERVICE_KEY_RESPONSE <= 0x80FF) };
+        const { assert!(vfs_quota::MSG_VFS_QUOTA_STAT_RESPONSE < vfs_signed::MSG_VFS_SIGNED_REQUEST) };
+        const { assert!(vfs_handle::MSG_VFS_OPEN > vfs_signed::MSG_VFS_REGISTER_SERVICE_KEY_RESPONSE) };
+        const { assert!(vfs_handle::MSG_VFS_ATTACH_SHM_RESPONSE <= 0x80FF) };
+        const { assert!(vfs_watch::MSG_VFS_WATCH > vfs_handle::MSG_VFS_ATTACH_SHM_RESPONSE) };
+        const { assert!(vfs_watch::MSG_VFS_EVENT <= 0x80FF) };
+        const { assert!(vfs_mount::MSG_VFS_MOUNT > vfs_watch::MSG_VFS_EVENT) };
+        const { assert!(vfs_mount::MSG_VFS_MOUNTS_RESPONSE <= 0x80FF) };
+        const { assert!(vfs_caller::MSG_VFS_SET_CALLER_USER > vfs_mount::MSG_VFS_MOUNTS_RESPONSE) };
+        const { assert!(vfs_caller::MSG_VFS_SET_CALLER_USER_RESPONSE <= 0x80FF) };
+        const { assert!(vfs_batch::MSG_VFS_BATCH > vfs_caller::MSG_VFS_SET_CALLER_USER_RESPONSE) };
+        const { assert!(vfs_batch::MSG_VFS_BATCH_RESPONSE <= 0x80FF) };
+        const { assert!(vfs_xattr::MSG_VFS_SETXATTR > vfs_batch::MSG_VFS_BATCH_RESPONSE) };
+        const { assert!(vfs_xattr::MSG_VFS_LISTXATTR_RESPONSE <= 0x80FF) };
+        const { assert!(vfs_trash::MSG_VFS_TRASH_LIST > vfs_xattr::MSG_VFS_LISTXATTR_RESPONSE) };
+        const { assert!(vfs_trash::MSG_VFS_TRASH_PURGE_RESPONSE <= 0x80FF) };
+        const {
+            assert!(vfs_snapshot::MSG_VFS_SNAPSHOT_CREATE > vfs_trash::MSG_VFS_TRASH_PURGE_RESPONSE)
+        };
+        const { assert!(vfs_snapshot::MSG_VFS_SNAPSHOT_DELETE_RESPONSE <= 0x80FF) };
+        const { assert!(vfs_hash::MSG_VFS_HASH > vfs_snapshot::MSG_VFS_SNAPSHOT_DELETE_RESPONSE) };
+        const { assert!(vfs_hash::MSG_VFS_HASH_RESPONSE <= 0x80FF) };
 
         // Time service in 0x8100-0x810F
         const { assert!(time::MSG_GET_TIME_SETTINGS >= 0x8100) };
-        const { assert!(time::MSG_SET_TIME_SETTINGS_RESPONSE <= 0x810F) };
+        const { assert!(time::MSG_TIME_ALARM <= 0x810F) };
+
+        // Update service in 0x8200-0x820F
+        const { assert!(update::MSG_UPDATE_STAGE >= 0x8200) };
+        const { assert!(update::MSG_UPDATE_HEALTH_FAILED_RESPONSE <= 0x820F) };
+
+        // Feature flag service in 0x8300-0x830F
+        const { assert!(flags::MSG_FLAGS_GET >= 0x8300) };
+        const { assert!(flags::MSG_FLAGS_LIST_RESPONSE <= 0x830F) };
+
+        // Speech service in 0x8500-0x850F
+        const { assert!(speech::MSG_SPEAK >= 0x8500) };
+        const { assert!(speech::MSG_SPEECH_DONE <= 0x850F) };
+
+        // Event bus service in 0x8600-0x860F
+        const { assert!(events::MSG_EVENT_PUBLISH >= 0x8600) };
+        const { assert!(events::MSG_EVENT_SET_ACL_RESPONSE <= 0x860F) };
+
+        // Calendar service in 0x8700-0x870F
+        const { assert!(calendar::MSG_CAL_ADD >= 0x8700) };
+        const { assert!(calendar::MSG_CAL_IMPORT_RESPONSE <= 0x870F) };
+
+        // Contacts service in 0x8800-0x880F
+        const { assert!(contacts::MSG_CONTACT_ADD >= 0x8800) };
+        const { assert!(contacts::MSG_CONTACT_MY_CARD_RESPONSE <= 0x880F) };
+
+        // Messaging service in 0x8900-0x890F
+        const { assert!(messaging::MSG_MESSAGING_OPEN >= 0x8900) };
+        const { assert!(messaging::MSG_MESSAGING_SEND_CLIPBOARD_RESPONSE <= 0x890F) };
+
+        // Clipboard service in 0x8E00-0x8E0F
+        const { assert!(clipboard::MSG_CLIPBOARD_COPY >= 0x8E00) };
+        const { assert!(clipboard::MSG_CLIPBOARD_REMOTE <= 0x8E0F) };
+
+        // Print service in 0x8A00-0x8A0F
+        const { assert!(print::MSG_PRINT_SUBMIT >= 0x8A00) };
+        const { assert!(print::MSG_PRINT_DIALOG_DONE <= 0x8A0F) };
+
+        // Thumbnail service in 0x8B00-0x8B0F
+        const { assert!(thumbnail::MSG_THUMBNAIL_GET >= 0x8B00) };
+        const { assert!(thumbnail::MSG_THUMBNAIL_DECODED <= 0x8B0F) };
+
+        // Archive service in 0x8C00-0x8C0F
+        const { assert!(archive::MSG_ARCHIVE_CREATE >= 0x8C00) };
+        const { assert!(archive::MSG_ARCHIVE_STATUS_RESPONSE <= 0x8C0F) };
+
+        // Command service in 0x8D00-0x8D0F
+        const { assert!(commands::MSG_COMMAND_REGISTER >= 0x8D00) };
+        const { assert!(commands::MSG_COMMAND <= 0x8D0F) };
+
+        // Request control in 0x0010-0x001F
+        const { assert!(request::MSG_CANCEL_REQUEST >= 0x0010) };
+        const { assert!(request::MSG_CANCEL_REQUEST <= 0x001F) };
 
         // Keystore service in 0xA000-0xA0FF
         const { assert!(keystore_svc::MSG_KEYSTORE_READ >= 0xA000) };
         const { assert!(keystore_svc::MSG_KEYSTORE_LIST_RESPONSE <= 0xA0FF) };
     }
 
+    #[test]
+    fn test_frame_roundtrip() {
+        use frame::{Frame, FrameError};
+
+        let frame = Frame {
+            from_pid: 7,
+            tag: 0x8001,
+            cap_slots: alloc::vec![3, 9],
+            correlation_id: Some(42),
+            data: b"hello",
+        };
+        let buf = frame.encode();
+        assert_eq!(buf.len(), Frame::MIN_HEADER_LEN + 8 + 5);
+        assert_eq!(Frame::decode(&buf), Ok(frame));
+
+        // 0 on the wire is no correlation ID
+        let plain = Frame {
+            from_pid: 0,
+            tag: 1,
+            cap_slots: alloc::vec![],
+            correlation_id: None,
+            data: b"",
+        };
+        let buf = plain.encode();
+        assert_eq!(&buf[9..13], &[0; 4]);
+        assert_eq!(Frame::decode(&buf), Ok(plain));
+
+        // Cap slots count towards the header
+        let with_cap = Frame {
+            cap_slots: alloc::vec![1],
+            ..Frame::decode(&buf).unwrap()
+        };
+        let mut buf = with_cap.encode();
+        buf.truncate(Frame::MIN_HEADER_LEN + 3);
+        assert_eq!(Frame::decode(&buf), Err(FrameError::TooShort));
+    }
+
+    #[test]
+    fn test_storage_result_roundtrip() {
+        use storage::{ResultKind, StorageResult};
+
+        let result = StorageResult::new(42, ResultKind::ReadOk, b"hello");
+        let payload = result.encode();
+        assert_eq!(payload.len(), StorageResult::HEADER_LEN + 5);
+        assert_eq!(&payload[..4], &42u32.to_le_bytes());
+        assert_eq!(payload[4], storage::result::READ_OK);
+        assert_eq!(StorageResult::decode(&payload), Ok(result));
+
+        let payload = StorageResult::exists(7, true).encode();
+        let exists = StorageResult::decode(&payload).unwrap();
+        assert_eq!(exists.kind, ResultKind::ExistsOk);
+        assert!(exists.exists_flag());
+
+        for value in 0..=6u8 {
+            let kind = ResultKind::from_u8(value).expect("valid value");
+            assert_eq!(kind as u8, value);
+        }
+        assert!(ResultKind::from_u8(7).is_none());
+    }
+
+    #[test]
+    fn test_storage_result_decode_errors() {
+        use storage::{DecodeError, ResultKind, StorageResult};
+
+        assert_eq!(StorageResult::decode(&[0; 8]), Err(DecodeError::TooShort));
+
+        let mut payload = StorageResult::new(1, ResultKind::WriteOk, &[]).encode();
+        payload[4] = 0xEE;
+        assert_eq!(
+            StorageResult::decode(&payload),
+            Err(DecodeError::UnknownKind(0xEE))
+        );
+
+        let payload = StorageResult::new(1, ResultKind::ListOk, b"[]").encode();
+        assert_eq!(
+            StorageResult::decode(&payload[..10]),
+            Err(DecodeError::Truncated {
+                declared: 2,
+                available: 1
+            })
+        );
+    }
+
     #[test]
     fn test_object_type_canonical_values() {
         // CRITICAL: These values MUST NOT change!
@@ -1129,17 +2794,19 @@ mod tests {
         assert_eq!(ObjectType::Filesystem as u8, 9);
         assert_eq!(ObjectType::Identity as u8, 10);
         assert_eq!(ObjectType::Keystore as u8, 11);
+        assert_eq!(ObjectType::Notification as u8, 12);
+        assert_eq!(ObjectType::Debugger as u8, 13);
     }
 
     #[test]
     fn test_object_type_from_u8_roundtrip() {
-        for val in 1..=11u8 {
+        for val in 1..=13u8 {
             let obj_type = ObjectType::from_u8(val).expect("valid value");
             assert_eq!(obj_type as u8, val);
         }
         // Invalid values should return None
         assert!(ObjectType::from_u8(0).is_none());
-        assert!(ObjectType::from_u8(12).is_none());
+        assert!(ObjectType::from_u8(14).is_none());
         assert!(ObjectType::from_u8(255).is_none());
     }
 }
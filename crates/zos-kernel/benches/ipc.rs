@@ -0,0 +1,156 @@
+//! IPC benchmarks
+//!
+//! Capability lookup runs on every send and receive. This measures slot
+//! lookup on its own, against a plain BTreeMap slot table, the full
+//! `axiom_check`, and a send/receive round trip through the System with
+//! the sender's endpoint capability in a dense (low) and a sparse (high)
+//! slot.
+//!
+//! Run with: `cargo bench -p zos-kernel --bench ipc`
+
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use zos_axiom::capability::DENSE_SLOTS;
+use zos_axiom::CapSlots;
+use zos_hal::TestHal;
+use zos_kernel::{
+    axiom_check, CapSlot, Capability, CapabilitySpace, ObjectType, Permissions, ProcessId, System,
+};
+
+/// Lookups per lookup measurement.
+const LOOKUPS: usize = 1 << 20;
+
+/// Round trips per IPC measurement. Each one commits a message, so this
+/// stays well under the CommitLog cap and trimming never kicks in.
+const ROUND_TRIPS: usize = 4_096;
+
+/// Capabilities held by the looked-up process.
+const TABLE_SIZES: [usize; 3] = [4, 16, 64];
+
+/// Runs per measurement; the fastest is reported.
+const RUNS: usize = 20;
+
+fn cap(id: u64) -> Capability {
+    Capability {
+        id,
+        object_type: ObjectType::Endpoint,
+        object_id: id,
+        permissions: Permissions::full(),
+        generation: 0,
+        expires_at: 0,
+    }
+}
+
+fn best_of(mut run: impl FnMut() -> Duration) -> Duration {
+    (0..RUNS).map(|_| run()).min().unwrap_or_default()
+}
+
+fn per_op(elapsed: Duration, ops: usize) -> f64 {
+    elapsed.as_nanos() as f64 / ops as f64
+}
+
+/// Time `LOOKUPS` lookups cycling through `size` slots.
+fn time_lookups(size: usize, mut lookup: impl FnMut(CapSlot)) -> Duration {
+    best_of(|| {
+        let start = Instant::now();
+        for i in 0..LOOKUPS {
+            lookup(black_box((i % size) as CapSlot));
+        }
+        start.elapsed()
+    })
+}
+
+fn bench_lookup(size: usize) {
+    let mut cspace = CapabilitySpace::new();
+    let mut btree = BTreeMap::new();
+    let mut slots = CapSlots::new();
+    for id in 0..size as u64 {
+        let slot = cspace.insert(cap(id));
+        btree.insert(slot, cap(id));
+        slots.insert(slot, cap(id));
+    }
+    let write = Permissions::write_only();
+
+    let btree_time = time_lookups(size, |slot| {
+        black_box(btree.get(&slot));
+    });
+    let slots_time = time_lookups(size, |slot| {
+        black_box(slots.get(&slot));
+    });
+    let check_time = time_lookups(size, |slot| {
+        let _ = black_box(axiom_check(
+            &cspace,
+            slot,
+            &write,
+            Some(ObjectType::Endpoint),
+            0,
+        ));
+    });
+
+    println!(
+        "  {:>3} caps   btree {:>6.2} ns  slots {:>6.2} ns  {:.2}x   axiom_check {:>6.2} ns",
+        size,
+        per_op(btree_time, LOOKUPS),
+        per_op(slots_time, LOOKUPS),
+        btree_time.as_secs_f64() / slots_time.as_secs_f64(),
+        per_op(check_time, LOOKUPS)
+    );
+}
+
+/// A sender holding `filler` endpoint caps before the one it sends on.
+fn ipc_system(filler: usize) -> (System<TestHal>, ProcessId, CapSlot, ProcessId, CapSlot) {
+    let mut system = System::new(TestHal::new());
+    let sender = system.register_process("sender");
+    let receiver = system.register_process("receiver");
+    for _ in 0..filler {
+        system
+            .create_endpoint(sender)
+            .expect("create filler endpoint");
+    }
+    let (_, recv_slot) = system.create_endpoint(receiver).expect("create endpoint");
+    let send_slot = system
+        .grant_capability(receiver, recv_slot, sender, Permissions::write_only())
+        .expect("grant send capability");
+    (system, sender, send_slot, receiver, recv_slot)
+}
+
+fn bench_round_trip(label: &str, filler: usize) {
+    let mut slot = 0;
+    let elapsed = best_of(|| {
+        let (mut system, sender, send_slot, receiver, recv_slot) = ipc_system(filler);
+        slot = send_slot;
+        let start = Instant::now();
+        for i in 0..ROUND_TRIPS {
+            system
+                .ipc_send(sender, send_slot, 0x8000, vec![i as u8; 32])
+                .expect("send");
+            black_box(system.ipc_receive(receiver, recv_slot).expect("receive"));
+        }
+        start.elapsed()
+    });
+    println!(
+        "  {:<7} slot {:>3}   {:>8.1} ns/round trip",
+        label,
+        slot,
+        per_op(elapsed, ROUND_TRIPS)
+    );
+}
+
+fn main() {
+    println!(
+        "capability lookup: {} lookups, best of {} runs",
+        LOOKUPS, RUNS
+    );
+    for size in TABLE_SIZES {
+        bench_lookup(size);
+    }
+
+    println!(
+        "ipc send/receive: {} round trips, best of {} runs",
+        ROUND_TRIPS, RUNS
+    );
+    bench_round_trip("dense", 0);
+    bench_round_trip("sparse", DENSE_SLOTS + 8);
+}
@@ -0,0 +1,223 @@
+//! Synchronous calls
+//!
+//! `SYS_CALL` sends a request and parks the caller until it is answered.
+//! The request carries the call's ID, and whoever receives it is handed the
+//! reply capability: the one-shot right to answer that caller with
+//! `SYS_REPLY`. The reply goes straight to the call, not through an
+//! endpoint queue, and the caller collects it with `SYS_CALL_WAIT`.
+//!
+//! A call ends with its reply, or fails when its deadline passes or the
+//! process that had to reply dies: the callee holding the reply capability,
+//! or the endpoint's owner while the request is still queued. A process has
+//! at most one call in progress. The reply capability is consumed by
+//! replying and revoked when the call ends any other way, so a late reply
+//! fails rather than landing in a later call.
+
+use alloc::collections::BTreeMap;
+
+use crate::error::KernelError;
+use crate::ipc::Message;
+use crate::types::{EndpointId, ProcessId};
+
+/// Call identifier, unique since boot.
+pub type CallId = u32;
+
+/// A call in progress.
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub id: CallId,
+    /// Process waiting for the reply
+    pub caller: ProcessId,
+    /// Endpoint the request was sent to
+    pub endpoint: EndpointId,
+    /// Process that received the request and holds the reply capability
+    pub callee: Option<ProcessId>,
+    /// Uptime (nanos) the caller stops waiting at
+    pub deadline: Option<u64>,
+    /// The reply, once sent
+    pub reply: Option<Message>,
+}
+
+/// Why a call failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallError {
+    /// The request could not be sent
+    Send(KernelError),
+    /// The caller already has a call in progress
+    Busy,
+    /// No reply by the call's deadline
+    TimedOut,
+    /// The process that had to reply died first
+    PeerDied,
+    /// The caller has no call in progress
+    NoCall,
+}
+
+/// Every call in progress, by caller.
+#[derive(Debug)]
+pub struct CallTable {
+    calls: BTreeMap<ProcessId, Call>,
+    next_id: CallId,
+}
+
+impl Default for CallTable {
+    fn default() -> Self {
+        Self {
+            calls: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl CallTable {
+    /// Start a call by `caller` to `endpoint`.
+    pub fn begin(
+        &mut self,
+        caller: ProcessId,
+        endpoint: EndpointId,
+        deadline: Option<u64>,
+    ) -> Result<CallId, CallError> {
+        if self.calls.contains_key(&caller) {
+            return Err(CallError::Busy);
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        self.calls.insert(
+            caller,
+            Call {
+                id,
+                caller,
+                endpoint,
+                callee: None,
+                deadline,
+                reply: None,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Hand the reply capability of `caller`'s call `id` to `callee`, which
+    /// received its request. Returns whether it was handed over: not if the
+    /// call has ended since the request was sent.
+    pub fn accept(&mut self, caller: ProcessId, id: CallId, callee: ProcessId) -> bool {
+        match self.calls.get_mut(&caller) {
+            Some(call) if call.id == id && call.callee.is_none() => {
+                call.callee = Some(callee);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Answer `caller`'s call with `reply`, using `callee`'s reply
+    /// capability.
+    pub fn reply(
+        &mut self,
+        callee: ProcessId,
+        caller: ProcessId,
+        reply: Message,
+    ) -> Result<(), CallError> {
+        match self.calls.get_mut(&caller) {
+            Some(call) if call.callee == Some(callee) && call.reply.is_none() => {
+                call.reply = Some(reply);
+                Ok(())
+            }
+            _ => Err(CallError::NoCall),
+        }
+    }
+
+    /// `caller`'s call in progress.
+    pub fn get(&self, caller: ProcessId) -> Option<&Call> {
+        self.calls.get(&caller)
+    }
+
+    /// End `caller`'s call, revoking its reply capability.
+    pub fn finish(&mut self, caller: ProcessId) -> Option<Call> {
+        self.calls.remove(&caller)
+    }
+
+    /// Number of calls in progress.
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    /// Whether no calls are in progress.
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const CALLER: ProcessId = ProcessId(5);
+    const CALLEE: ProcessId = ProcessId(6);
+
+    fn message(from: ProcessId) -> Message {
+        Message {
+            from,
+            tag: 1,
+            data: vec![],
+            transferred_caps: vec![],
+            deadline: None,
+            correlation_id: None,
+            call: None,
+            broadcast: None,
+        }
+    }
+
+    #[test]
+    fn test_one_call_per_caller() {
+        let mut calls = CallTable::default();
+        let id = calls.begin(CALLER, EndpointId(9), None).unwrap();
+        assert_eq!(
+            calls.begin(CALLER, EndpointId(9), None).unwrap_err(),
+            CallError::Busy
+        );
+        assert!(calls.begin(CALLEE, EndpointId(9), None).is_ok());
+
+        assert_eq!(calls.finish(CALLER).unwrap().id, id);
+        assert_ne!(calls.begin(CALLER, EndpointId(9), None).unwrap(), id);
+    }
+
+    #[test]
+    fn test_reply_capability_is_one_shot() {
+        let mut calls = CallTable::default();
+        let id = calls.begin(CALLER, EndpointId(9), None).unwrap();
+
+        // Only the receiver of the request may reply, once
+        assert_eq!(
+            calls.reply(CALLEE, CALLER, message(CALLEE)),
+            Err(CallError::NoCall)
+        );
+        assert!(!calls.accept(CALLER, id + 1, CALLEE));
+        assert!(calls.accept(CALLER, id, CALLEE));
+        assert!(!calls.accept(CALLER, id, ProcessId(7)));
+        assert_eq!(
+            calls.reply(ProcessId(7), CALLER, message(CALLEE)),
+            Err(CallError::NoCall)
+        );
+        assert_eq!(calls.reply(CALLEE, CALLER, message(CALLEE)), Ok(()));
+        assert_eq!(
+            calls.reply(CALLEE, CALLER, message(CALLEE)),
+            Err(CallError::NoCall)
+        );
+        assert!(calls.get(CALLER).unwrap().reply.is_some());
+    }
+
+    #[test]
+    fn test_finished_call_revokes_reply_capability() {
+        let mut calls = CallTable::default();
+        let id = calls.begin(CALLER, EndpointId(9), None).unwrap();
+        calls.finish(CALLER);
+        assert!(!calls.accept(CALLER, id, CALLEE));
+
+        // A new call does not inherit the old request's capability
+        let next = calls.begin(CALLER, EndpointId(9), None).unwrap();
+        assert!(!calls.accept(CALLER, id, CALLEE));
+        assert!(calls.accept(CALLER, next, CALLEE));
+        assert_eq!(calls.len(), 1);
+    }
+}
@@ -0,0 +1,246 @@
+//! Fault injection for resilience testing
+//!
+//! When enabled, the [`FaultInjector`] decides - deterministically from a
+//! seed - which operations fail:
+//!
+//! - **Storage**: results are delayed or dropped before reaching the caller
+//! - **IPC**: a percentage of `SYS_SEND` calls fail with a transient error
+//! - **Processes**: random non-core processes are killed
+//!
+//! Each fault class draws from its own stream derived from the seed, so the
+//! decisions for one class do not shift when traffic of another class
+//! changes. The seed is recorded in the CommitLog as
+//! `CommitType::FaultInjectionSeeded`, which makes any failure found this way
+//! reproducible by re-running with the same seed.
+
+/// Fault class bit: delay or drop storage results.
+pub const FAULT_STORAGE: u8 = 1 << 0;
+/// Fault class bit: fail IPC sends with a transient error.
+pub const FAULT_IPC: u8 = 1 << 1;
+/// Fault class bit: kill random non-core processes.
+pub const FAULT_KILL: u8 = 1 << 2;
+
+/// Result code returned by a `SYS_SEND` that was failed by fault injection.
+///
+/// Distinct from the generic `-1` so callers can tell an injected transient
+/// failure from a real capability error.
+pub const TRANSIENT_SEND_ERROR: i64 = zos_ipc::syscall_error::TRANSIENT as i64;
+
+/// Fault rates. Percentages are per decision (per storage result, per send,
+/// per kill check).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChaosConfig {
+    /// Enabled fault classes (`FAULT_*` bits)
+    pub faults: u8,
+    /// Chance a storage result is delayed
+    pub storage_delay_pct: u8,
+    /// Chance a storage result is dropped
+    pub storage_drop_pct: u8,
+    /// Upper bound for a storage delay
+    pub max_storage_delay_ms: u32,
+    /// Chance an IPC send fails
+    pub send_fail_pct: u8,
+    /// Chance a kill check kills a process
+    pub kill_pct: u8,
+}
+
+impl ChaosConfig {
+    /// Default rates for the given fault classes.
+    pub fn with_faults(faults: u8) -> Self {
+        Self {
+            faults,
+            storage_delay_pct: 20,
+            storage_drop_pct: 2,
+            max_storage_delay_ms: 2000,
+            send_fail_pct: 2,
+            kill_pct: 5,
+        }
+    }
+
+    /// Whether a fault class is enabled.
+    pub fn has(&self, fault: u8) -> bool {
+        self.faults & fault != 0
+    }
+}
+
+/// What to do with a storage result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageFault {
+    /// Deliver normally
+    Deliver,
+    /// Deliver after the given delay
+    Delay { ms: u32 },
+    /// Never deliver
+    Drop,
+}
+
+/// SplitMix64 - small, fast and good enough for fault decisions.
+#[derive(Clone, Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..bound` (`bound` > 0).
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn chance(&mut self, pct: u8) -> bool {
+        pct > 0 && self.below(100) < pct as u64
+    }
+}
+
+/// Seeded fault injector.
+#[derive(Clone, Debug)]
+pub struct FaultInjector {
+    seed: u64,
+    config: ChaosConfig,
+    storage: SplitMix64,
+    ipc: SplitMix64,
+    kill: SplitMix64,
+}
+
+impl FaultInjector {
+    /// Create an injector. The same seed and config always produce the same
+    /// sequence of decisions per fault class.
+    pub fn new(seed: u64, config: ChaosConfig) -> Self {
+        Self {
+            seed,
+            config,
+            storage: SplitMix64(seed ^ 0x5354_4f52_4147_4500),
+            ipc: SplitMix64(seed ^ 0x4950_4300_0000_0000),
+            kill: SplitMix64(seed ^ 0x4b49_4c4c_0000_0000),
+        }
+    }
+
+    /// Seed this injector was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Active configuration.
+    pub fn config(&self) -> &ChaosConfig {
+        &self.config
+    }
+
+    /// Decide the fate of a storage result.
+    pub fn storage_fault(&mut self) -> StorageFault {
+        if !self.config.has(FAULT_STORAGE) {
+            return StorageFault::Deliver;
+        }
+        if self.storage.chance(self.config.storage_drop_pct) {
+            return StorageFault::Drop;
+        }
+        if self.storage.chance(self.config.storage_delay_pct) {
+            let max = self.config.max_storage_delay_ms.max(1) as u64;
+            return StorageFault::Delay {
+                ms: 1 + self.storage.below(max) as u32,
+            };
+        }
+        StorageFault::Deliver
+    }
+
+    /// Whether the next IPC send should fail.
+    pub fn should_fail_send(&mut self) -> bool {
+        self.config.has(FAULT_IPC) && self.ipc.chance(self.config.send_fail_pct)
+    }
+
+    /// Pick a process to kill from `candidates`, or `None` to spare them all
+    /// this round. Callers must exclude core processes.
+    pub fn pick_victim<T: Copy>(&mut self, candidates: &[T]) -> Option<T> {
+        if !self.config.has(FAULT_KILL) || candidates.is_empty() {
+            return None;
+        }
+        if !self.kill.chance(self.config.kill_pct) {
+            return None;
+        }
+        let index = self.kill.below(candidates.len() as u64) as usize;
+        Some(candidates[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn all_faults() -> ChaosConfig {
+        ChaosConfig::with_faults(FAULT_STORAGE | FAULT_IPC | FAULT_KILL)
+    }
+
+    #[test]
+    fn test_same_seed_same_decisions() {
+        let mut a = FaultInjector::new(42, all_faults());
+        let mut b = FaultInjector::new(42, all_faults());
+        for _ in 0..500 {
+            assert_eq!(a.storage_fault(), b.storage_fault());
+            assert_eq!(a.should_fail_send(), b.should_fail_send());
+            assert_eq!(
+                a.pick_victim(&[10u64, 11, 12]),
+                b.pick_victim(&[10u64, 11, 12])
+            );
+        }
+    }
+
+    #[test]
+    fn test_streams_are_independent() {
+        let mut a = FaultInjector::new(7, all_faults());
+        let mut b = FaultInjector::new(7, all_faults());
+        // Extra storage traffic on `a` must not change its IPC decisions
+        for _ in 0..100 {
+            a.storage_fault();
+        }
+        let sends_a: Vec<bool> = (0..200).map(|_| a.should_fail_send()).collect();
+        let sends_b: Vec<bool> = (0..200).map(|_| b.should_fail_send()).collect();
+        assert_eq!(sends_a, sends_b);
+    }
+
+    #[test]
+    fn test_disabled_classes_never_fault() {
+        let mut injector = FaultInjector::new(1, ChaosConfig::with_faults(FAULT_STORAGE));
+        for _ in 0..1000 {
+            assert!(!injector.should_fail_send());
+            assert_eq!(injector.pick_victim(&[5u64]), None);
+        }
+    }
+
+    #[test]
+    fn test_rates_are_roughly_respected() {
+        let mut config = all_faults();
+        config.send_fail_pct = 10;
+        let mut injector = FaultInjector::new(0xDEAD_BEEF, config);
+        let failures = (0..10_000).filter(|_| injector.should_fail_send()).count();
+        assert!((800..1200).contains(&failures), "{} failures", failures);
+    }
+
+    #[test]
+    fn test_storage_delay_within_bounds() {
+        let mut config = all_faults();
+        config.storage_drop_pct = 0;
+        config.storage_delay_pct = 100;
+        config.max_storage_delay_ms = 50;
+        let mut injector = FaultInjector::new(3, config);
+        for _ in 0..1000 {
+            match injector.storage_fault() {
+                StorageFault::Delay { ms } => assert!((1..=50).contains(&ms)),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_pick_victim_empty_candidates() {
+        let mut config = all_faults();
+        config.kill_pct = 100;
+        let mut injector = FaultInjector::new(9, config);
+        assert_eq!(injector.pick_victim::<u64>(&[]), None);
+        assert_eq!(injector.pick_victim(&[33u64]), Some(33));
+    }
+}
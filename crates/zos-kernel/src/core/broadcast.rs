@@ -0,0 +1,223 @@
+//! Broadcast endpoints for KernelCore.
+//!
+//! A broadcast endpoint is created like any endpoint (`EndpointCreated`
+//! and the owner's `CapInserted`), and each publish is one `MessageSent`
+//! commit to it, whatever the number of subscribers. Subscriptions and the
+//! copies queued for subscribers are volatile like message queues and are
+//! not replayed.
+
+use alloc::vec::Vec;
+
+use crate::error::KernelError;
+use crate::ipc::{
+    BroadcastError, Message, Subscribers, MAX_BROADCASTS_PER_PROCESS, MAX_BROADCAST_BACKLOG,
+    MAX_MESSAGE_SIZE, MAX_QUEUED_MESSAGES,
+};
+use crate::types::{CapSlot, EndpointId, ProcessId};
+use zos_axiom::{Commit, CommitType};
+use zos_hal::HAL;
+
+use super::KernelCore;
+
+impl<H: HAL> KernelCore<H> {
+    /// Create a broadcast endpoint owned by a process, with a
+    /// full-permission capability for it.
+    ///
+    /// Returns (Result<(EndpointId, CapSlot), BroadcastError>, Vec<Commit>).
+    pub fn create_broadcast(
+        &mut self,
+        owner: ProcessId,
+        timestamp: u64,
+    ) -> (Result<(EndpointId, CapSlot), BroadcastError>, Vec<Commit>) {
+        let owned = self
+            .endpoints
+            .values()
+            .filter(|ep| ep.owner == owner && ep.is_broadcast())
+            .count();
+        if owned >= MAX_BROADCASTS_PER_PROCESS {
+            return (Err(BroadcastError::LimitExceeded), Vec::new());
+        }
+
+        let (result, commits) = self.create_endpoint(owner, timestamp);
+        let result = result.map_err(BroadcastError::Capability);
+        if let Ok((id, _)) = result {
+            if let Some(endpoint) = self.endpoints.get_mut(&id) {
+                endpoint.broadcast = Some(Subscribers::default());
+            }
+        }
+        (result, commits)
+    }
+
+    /// Subscribe `pid`'s endpoint in `endpoint_slot` to the broadcast
+    /// endpoint in `broadcast_slot`, replacing its earlier subscription
+    /// (needs read permission on both).
+    pub fn subscribe(
+        &mut self,
+        pid: ProcessId,
+        broadcast_slot: CapSlot,
+        endpoint_slot: CapSlot,
+        timestamp: u64,
+    ) -> Result<(), BroadcastError> {
+        let broadcast_id = self.validate_broadcast_cap(pid, broadcast_slot, false, timestamp)?;
+        let endpoint_id = self
+            .validate_receive_cap(pid, endpoint_slot, timestamp)
+            .map_err(BroadcastError::Capability)?;
+        if self
+            .endpoints
+            .get(&endpoint_id)
+            .is_none_or(|ep| ep.is_broadcast())
+        {
+            return Err(BroadcastError::WrongKind);
+        }
+
+        self.subscribers_mut(broadcast_id)?
+            .subscribe(pid, endpoint_id)
+    }
+
+    /// Cancel `pid`'s subscription to the broadcast endpoint in `slot`, if
+    /// it has one (needs read permission).
+    pub fn unsubscribe(
+        &mut self,
+        pid: ProcessId,
+        slot: CapSlot,
+        timestamp: u64,
+    ) -> Result<(), BroadcastError> {
+        let broadcast_id = self.validate_broadcast_cap(pid, slot, false, timestamp)?;
+        self.subscribers_mut(broadcast_id)?.unsubscribe(pid);
+        Ok(())
+    }
+
+    /// Queue a copy of a message on every subscriber of the broadcast
+    /// endpoint in `slot` (needs write permission).
+    ///
+    /// Subscribers with `MAX_BROADCAST_BACKLOG` of its messages still
+    /// queued, or with a full endpoint, miss this one. The copies carry no
+    /// deadline or correlation ID: they announce something rather than
+    /// continue the publisher's request.
+    ///
+    /// Returns (Result<usize, BroadcastError>, Option<Commit>) with the
+    /// number of subscribers the message was queued for.
+    pub fn publish(
+        &mut self,
+        pid: ProcessId,
+        slot: CapSlot,
+        tag: u32,
+        data: Vec<u8>,
+        timestamp: u64,
+    ) -> (Result<usize, BroadcastError>, Option<Commit>) {
+        let broadcast_id = match self.validate_broadcast_cap(pid, slot, true, timestamp) {
+            Ok(id) => id,
+            Err(e) => return (Err(e), None),
+        };
+        if data.len() > MAX_MESSAGE_SIZE {
+            return (Err(BroadcastError::TooLarge), None);
+        }
+
+        // Take the subscribers out while their endpoints are written to
+        let mut subscribers = match self.subscribers_mut(broadcast_id) {
+            Ok(subscribers) => core::mem::take(subscribers),
+            Err(e) => return (Err(e), None),
+        };
+        subscribers.prune(|id| self.endpoints.contains_key(&id));
+
+        let data_len = data.len();
+        let mut delivered = Vec::new();
+        for subscriber in subscribers.iter_mut() {
+            let Some(endpoint) = self.endpoints.get_mut(&subscriber.endpoint) else {
+                continue;
+            };
+            if endpoint.pending_messages.len() >= MAX_QUEUED_MESSAGES
+                || endpoint.backlog_from(broadcast_id) >= MAX_BROADCAST_BACKLOG
+            {
+                subscriber.dropped += 1;
+                continue;
+            }
+            endpoint.pending_messages.push_back(Message {
+                from: pid,
+                tag,
+                data: data.clone(),
+                transferred_caps: Vec::new(),
+                deadline: None,
+                correlation_id: None,
+                call: None,
+                broadcast: Some(broadcast_id),
+            });
+            delivered.push(subscriber.endpoint);
+        }
+
+        if let Some(broadcast) = self.endpoints.get_mut(&broadcast_id) {
+            broadcast.metrics.total_messages += 1;
+            broadcast.metrics.total_bytes += data_len as u64;
+            broadcast.broadcast = Some(subscribers);
+        }
+        for &endpoint_id in &delivered {
+            self.update_send_metrics(pid, endpoint_id, data_len, timestamp);
+        }
+
+        let commit = Commit {
+            id: [0u8; 32],
+            prev_commit: [0u8; 32],
+            seq: 0,
+            timestamp,
+            commit_type: CommitType::MessageSent {
+                from_pid: pid.0,
+                to_endpoint: broadcast_id.0,
+                tag,
+                size: data_len,
+            },
+            caused_by: None,
+        };
+
+        (Ok(delivered.len()), Some(commit))
+    }
+
+    /// Subscribers of a broadcast endpoint
+    pub fn broadcast_subscribers(&self, id: EndpointId) -> Option<&Subscribers> {
+        self.endpoints.get(&id)?.broadcast.as_ref()
+    }
+
+    /// Drop a process's subscriptions, and those of endpoints that no
+    /// longer exist.
+    pub(super) fn release_process_subscriptions(&mut self, pid: ProcessId) {
+        let live: Vec<EndpointId> = self.endpoints.keys().copied().collect();
+        for subscribers in self
+            .endpoints
+            .values_mut()
+            .filter_map(|ep| ep.broadcast.as_mut())
+        {
+            subscribers.unsubscribe(pid);
+            subscribers.prune(|id| live.contains(&id));
+        }
+    }
+
+    fn subscribers_mut(&mut self, id: EndpointId) -> Result<&mut Subscribers, BroadcastError> {
+        self.endpoints
+            .get_mut(&id)
+            .ok_or(BroadcastError::Capability(KernelError::EndpointNotFound))?
+            .broadcast
+            .as_mut()
+            .ok_or(BroadcastError::WrongKind)
+    }
+
+    /// Check the capability in `slot` for a broadcast endpoint: write
+    /// permission to publish, read permission to (un)subscribe.
+    fn validate_broadcast_cap(
+        &self,
+        pid: ProcessId,
+        slot: CapSlot,
+        publish: bool,
+        timestamp: u64,
+    ) -> Result<EndpointId, BroadcastError> {
+        let id = if publish {
+            self.validate_send_cap(pid, slot, timestamp)
+        } else {
+            self.validate_receive_cap(pid, slot, timestamp)
+        }
+        .map_err(BroadcastError::Capability)?;
+        match self.endpoints.get(&id) {
+            Some(ep) if ep.is_broadcast() => Ok(id),
+            Some(_) => Err(BroadcastError::WrongKind),
+            None => Err(BroadcastError::Capability(KernelError::EndpointNotFound)),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Synchronous calls for KernelCore.
+//!
+//! Sending a call's request is a `MessageSent` commit like any send. The
+//! reply is kernel state like a deadline: it goes to the caller's call, not
+//! to an endpoint, so replying is not a commit.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::call::{CallError, CallId};
+use crate::error::KernelError;
+use crate::ipc::Message;
+use crate::types::{CapSlot, ProcessId, ProcessState};
+use zos_axiom::Commit;
+use zos_hal::HAL;
+
+use super::KernelCore;
+
+impl<H: HAL> KernelCore<H> {
+    /// Send a request to the endpoint in `endpoint_slot` and start waiting
+    /// for its reply, for at most `timeout_nanos` if given.
+    ///
+    /// The request carries the earlier of the caller's deadline and the
+    /// end of the timeout, so it is dropped unhandled once the caller has
+    /// stopped waiting. Returns the optional MessageSent commit.
+    pub fn ipc_call(
+        &mut self,
+        caller: ProcessId,
+        endpoint_slot: CapSlot,
+        tag: u32,
+        data: Vec<u8>,
+        timeout_nanos: Option<u64>,
+        timestamp: u64,
+    ) -> (Result<CallId, CallError>, Option<Commit>) {
+        if self.calls.get(caller).is_some() {
+            return (Err(CallError::Busy), None);
+        }
+        let endpoint_id = match self.validate_send_cap(caller, endpoint_slot, timestamp) {
+            Ok(id) => id,
+            Err(e) => return (Err(CallError::Send(e)), None),
+        };
+        if let Err(e) = self.admit_sender(endpoint_id, caller, timestamp) {
+            return (Err(CallError::Send(e)), None);
+        }
+
+        let timeout = timeout_nanos.map(|t| timestamp.saturating_add(t));
+        let deadline = match (self.deadline(caller), timeout) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        let id = match self.calls.begin(caller, endpoint_id, deadline) {
+            Ok(id) => id,
+            Err(e) => return (Err(e), None),
+        };
+
+        let message = Message {
+            from: caller,
+            tag,
+            data,
+            transferred_caps: vec![],
+            deadline,
+            correlation_id: self.correlation_id(caller),
+            call: Some(id),
+            broadcast: None,
+        };
+        match self.send_admitted(endpoint_id, message, timestamp) {
+            (Ok(()), commit) => (Ok(id), commit),
+            (Err(e), commit) => {
+                self.calls.finish(caller);
+                (Err(CallError::Send(e)), commit)
+            }
+        }
+    }
+
+    /// Answer `caller`'s call with `callee`'s reply capability.
+    ///
+    /// Fails with `InvalidCapability` unless `callee` received the call's
+    /// request and has not replied yet.
+    pub fn ipc_reply(
+        &mut self,
+        callee: ProcessId,
+        caller: ProcessId,
+        tag: u32,
+        data: Vec<u8>,
+        timestamp: u64,
+    ) -> Result<(), KernelError> {
+        let data_len = data.len();
+        let reply = Message {
+            from: callee,
+            tag,
+            data,
+            transferred_caps: vec![],
+            deadline: None,
+            correlation_id: self.correlation_id(callee),
+            call: None,
+            broadcast: None,
+        };
+        self.calls
+            .reply(callee, caller, reply)
+            .map_err(|_| KernelError::InvalidCapability)?;
+
+        if let Some(sender) = self.processes.get_mut(&callee) {
+            sender.metrics.ipc_sent += 1;
+            sender.metrics.ipc_bytes_sent += data_len as u64;
+            sender.metrics.last_active_ns = timestamp;
+        }
+        self.total_ipc_count += 1;
+        Ok(())
+    }
+
+    /// Collect the outcome of `caller`'s call.
+    ///
+    /// Returns `Ok(None)` while it is still waiting. Any other result ends
+    /// the call and revokes its reply capability.
+    pub fn call_wait(
+        &mut self,
+        caller: ProcessId,
+        timestamp: u64,
+    ) -> Result<Option<Message>, CallError> {
+        let call = self.calls.get(caller).ok_or(CallError::NoCall)?;
+        let outcome = if call.reply.is_some() {
+            Ok(())
+        } else if call.deadline.is_some_and(|d| d <= timestamp) {
+            Err(CallError::TimedOut)
+        } else if !self.can_reply(call.callee, call.endpoint) {
+            Err(CallError::PeerDied)
+        } else {
+            return Ok(None);
+        };
+
+        let call = self.calls.finish(caller).ok_or(CallError::NoCall)?;
+        outcome?;
+        let reply = call.reply.ok_or(CallError::NoCall)?;
+        if let Some(receiver) = self.processes.get_mut(&caller) {
+            receiver.metrics.ipc_received += 1;
+            receiver.metrics.ipc_bytes_received += reply.data.len() as u64;
+            receiver.metrics.last_active_ns = timestamp;
+        }
+        self.set_correlation_id(caller, reply.correlation_id);
+        Ok(Some(reply))
+    }
+
+    /// Whether a call can still be answered: its callee is alive, or, while
+    /// nobody has received the request, the endpoint and its owner are.
+    fn can_reply(&self, callee: Option<ProcessId>, endpoint: crate::types::EndpointId) -> bool {
+        let alive = |pid: ProcessId| {
+            self.processes
+                .get(&pid)
+                .is_some_and(|p| p.state != ProcessState::Zombie)
+        };
+        match callee {
+            Some(pid) => alive(pid),
+            None => self
+                .endpoints
+                .get(&endpoint)
+                .is_some_and(|e| alive(e.owner)),
+        }
+    }
+
+    /// Number of calls in progress.
+    pub fn call_count(&self) -> usize {
+        self.calls.len()
+    }
+}
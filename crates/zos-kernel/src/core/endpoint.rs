@@ -5,13 +5,12 @@
 //! - Listing endpoints
 //! - Getting endpoint details
 
-use alloc::collections::VecDeque;
 use alloc::vec;
 use alloc::vec::Vec;
 
 use crate::error::KernelError;
 use crate::ipc::{Endpoint, EndpointDetail, EndpointInfo, MessageSummary};
-use crate::types::{CapSlot, EndpointId, EndpointMetrics, ObjectType, ProcessId};
+use crate::types::{CapSlot, EndpointId, ObjectType, ProcessId};
 use crate::{Capability, Permissions};
 use zos_axiom::{Commit, CommitType};
 use zos_hal::HAL;
@@ -37,12 +36,7 @@ impl<H: HAL> KernelCore<H> {
         self.next_endpoint_id += 1;
 
         // Create and insert the endpoint
-        let endpoint = Endpoint {
-            id,
-            owner,
-            pending_messages: VecDeque::new(),
-            metrics: EndpointMetrics::default(),
-        };
+        let endpoint = Endpoint::new(id, owner);
         self.endpoints.insert(id, endpoint);
 
         // Grant full capability to owner
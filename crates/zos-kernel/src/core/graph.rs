@@ -0,0 +1,104 @@
+//! Service graph snapshots for KernelCore.
+//!
+//! Builds a [`ServiceGraph`] from the process table, the CSpaces, the name
+//! table and the per-sender message counts of each endpoint.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::graph::{ServiceEdge, ServiceGraph, ServiceNode};
+use crate::types::{EndpointId, NotificationId, ObjectType, ProcessId};
+use crate::Capability;
+use zos_hal::HAL;
+
+use super::KernelCore;
+
+impl<H: HAL> KernelCore<H> {
+    /// Snapshot how processes are wired to each other.
+    pub fn service_graph(&self) -> ServiceGraph {
+        let mut nodes: BTreeMap<ProcessId, ServiceNode> = self
+            .processes
+            .iter()
+            .map(|(pid, proc)| {
+                let node = ServiceNode {
+                    pid: *pid,
+                    name: proc.name.clone(),
+                    services: Vec::new(),
+                    endpoints: Vec::new(),
+                };
+                (*pid, node)
+            })
+            .collect();
+        for (name, binding) in self.names.iter() {
+            if let Some(node) = nodes.get_mut(&binding.pid) {
+                node.services.push(name.into());
+            }
+        }
+        for (id, endpoint) in &self.endpoints {
+            if let Some(node) = nodes.get_mut(&endpoint.owner) {
+                node.endpoints.push(*id);
+            }
+        }
+
+        let mut edges = BTreeMap::new();
+        for (holder, cspace) in &self.cap_spaces {
+            for cap in cspace.slots.values() {
+                let Some(owner) = self.object_owner(cap) else {
+                    continue;
+                };
+                if let Some(edge) = edge_mut(&mut edges, &nodes, *holder, owner) {
+                    edge.capabilities += 1;
+                }
+            }
+        }
+        for endpoint in self.endpoints.values() {
+            for (sender, count) in &endpoint.metrics.sent_by {
+                if let Some(edge) = edge_mut(&mut edges, &nodes, *sender, endpoint.owner) {
+                    edge.messages += count;
+                }
+            }
+        }
+
+        ServiceGraph {
+            nodes: nodes.into_values().collect(),
+            edges: edges.into_values().collect(),
+        }
+    }
+
+    /// The process owning the object `cap` refers to, for objects that have
+    /// an owner.
+    fn object_owner(&self, cap: &Capability) -> Option<ProcessId> {
+        match cap.object_type {
+            ObjectType::Endpoint => self
+                .endpoints
+                .get(&EndpointId(cap.object_id))
+                .map(|ep| ep.owner),
+            ObjectType::Notification => self
+                .notifications
+                .get(&NotificationId(cap.object_id))
+                .map(|n| n.owner),
+            ObjectType::Memory => self.shm.region(cap.object_id).map(|r| r.creator),
+            ObjectType::Process => Some(ProcessId(cap.object_id)),
+            _ => None,
+        }
+    }
+}
+
+/// The edge from `from` to `to`, added on first use. There are no edges to
+/// oneself or to or from processes that are gone.
+fn edge_mut<'a>(
+    edges: &'a mut BTreeMap<(ProcessId, ProcessId), ServiceEdge>,
+    nodes: &BTreeMap<ProcessId, ServiceNode>,
+    from: ProcessId,
+    to: ProcessId,
+) -> Option<&'a mut ServiceEdge> {
+    if from == to || !nodes.contains_key(&from) || !nodes.contains_key(&to) {
+        return None;
+    }
+    Some(edges.entry((from, to)).or_insert(ServiceEdge {
+        from,
+        to,
+        capabilities: 0,
+        messages: 0,
+    }))
+}
@@ -0,0 +1,190 @@
+//! Process groups for KernelCore.
+//!
+//! A process group is the set of processes sharing a `pgid`, the PID of the
+//! group's leader. Every process starts as the leader of its own group
+//! unless it is spawned into its spawner's, so a terminal can kill a
+//! pipeline it started with one group kill. A group lasts as long as it has
+//! members, even after its leader exits.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::error::KernelError;
+use crate::types::ProcessId;
+use zos_axiom::{Commit, CommitType};
+use zos_hal::HAL;
+
+use super::KernelCore;
+
+impl<H: HAL> KernelCore<H> {
+    /// Register a process in `group`, or as the leader of its own group
+    /// without one or if `group` has no members left.
+    ///
+    /// Returns the new PID and its ProcessCreated (and ProcessGroupChanged)
+    /// commits.
+    pub fn register_process_in_group(
+        &mut self,
+        name: &str,
+        group: Option<ProcessId>,
+        timestamp: u64,
+    ) -> (ProcessId, Vec<Commit>) {
+        let (pid, mut commits) = self.register_process(name, timestamp);
+        if let Some(pgid) = group.filter(|pgid| self.group_exists(*pgid)) {
+            commits.push(self.move_to_group(pid, pgid, timestamp));
+        }
+        (pid, commits)
+    }
+
+    /// Move `target` to process group `pgid` with authority checks.
+    ///
+    /// `pgid` 0 makes `target` the leader of a new group. The caller needs
+    /// authority over `target` (it is Init, the target itself, in the
+    /// target's group or holds a Process capability for it) and, to join an
+    /// existing group, over that group too: Init, a member of it, or a
+    /// Process capability for its leader. Joining a group is otherwise a way
+    /// into being able to kill it.
+    ///
+    /// Returns the group ID; moving a process to its own group records
+    /// nothing.
+    pub fn set_process_group_with_cap_check(
+        &mut self,
+        caller: ProcessId,
+        target: ProcessId,
+        pgid: ProcessId,
+        timestamp: u64,
+    ) -> (Result<ProcessId, KernelError>, Vec<Commit>) {
+        let current = match self.processes.get(&target) {
+            Some(proc) => proc.pgid,
+            None => return (Err(KernelError::ProcessNotFound), Vec::new()),
+        };
+        let pgid = if pgid.0 == 0 { target } else { pgid };
+
+        if !self.may_control_process(caller, target) {
+            self.hal.debug_write(&alloc::format!(
+                "[kernel] Set process group denied: PID {} has no authority over PID {}",
+                caller.0,
+                target.0
+            ));
+            return (Err(KernelError::PermissionDenied), Vec::new());
+        }
+        if pgid != target {
+            if !self.group_exists(pgid) {
+                return (Err(KernelError::ProcessNotFound), Vec::new());
+            }
+            if !self.may_control_group(caller, pgid) {
+                self.hal.debug_write(&alloc::format!(
+                    "[kernel] Set process group denied: PID {} has no authority over group {}",
+                    caller.0,
+                    pgid.0
+                ));
+                return (Err(KernelError::PermissionDenied), Vec::new());
+            }
+        }
+
+        if pgid == current {
+            return (Ok(pgid), Vec::new());
+        }
+        let commit = self.move_to_group(target, pgid, timestamp);
+        (Ok(pgid), vec![commit])
+    }
+
+    /// Kill the members of process group `pgid` that `caller` may kill.
+    ///
+    /// Init and members of the group may kill all of it; anyone else only
+    /// the members it holds a Process capability for. The caller itself is
+    /// never killed, so a terminal can kill the pipeline in its own group.
+    ///
+    /// Returns the number of processes killed, `ProcessNotFound` for a group
+    /// with no members, or `PermissionDenied` if the caller may kill none of
+    /// them.
+    pub fn kill_group_with_cap_check(
+        &mut self,
+        caller: ProcessId,
+        pgid: ProcessId,
+        timestamp: u64,
+    ) -> (Result<usize, KernelError>, Vec<Commit>) {
+        if !self.group_exists(pgid) {
+            return (Err(KernelError::ProcessNotFound), Vec::new());
+        }
+        let whole = caller.0 == 1 || self.pgid_of(caller) == Some(pgid);
+        let members: Vec<ProcessId> = self
+            .processes
+            .values()
+            .filter(|proc| proc.pgid == pgid && proc.pid != caller)
+            .map(|proc| proc.pid)
+            .collect();
+        let targets: Vec<ProcessId> = members
+            .iter()
+            .copied()
+            .filter(|pid| whole || self.has_kill_permission(caller, *pid))
+            .collect();
+        if targets.is_empty() && !members.is_empty() {
+            self.hal.debug_write(&alloc::format!(
+                "[kernel] Group kill denied: PID {} may kill no member of group {}",
+                caller.0,
+                pgid.0
+            ));
+            return (Err(KernelError::PermissionDenied), Vec::new());
+        }
+
+        self.hal.debug_write(&alloc::format!(
+            "[kernel] PID {} killing {} of {} processes in group {}",
+            caller.0,
+            targets.len(),
+            members.len(),
+            pgid.0
+        ));
+        let mut commits = Vec::new();
+        for pid in &targets {
+            commits.extend(self.kill_process(*pid, timestamp));
+        }
+        (Ok(targets.len()), commits)
+    }
+
+    /// The process group `pid` belongs to.
+    pub fn pgid_of(&self, pid: ProcessId) -> Option<ProcessId> {
+        self.processes.get(&pid).map(|proc| proc.pgid)
+    }
+
+    /// Whether any process belongs to group `pgid`.
+    fn group_exists(&self, pgid: ProcessId) -> bool {
+        self.processes.values().any(|proc| proc.pgid == pgid)
+    }
+
+    /// Whether `caller` may move `target` between groups: it is Init, the
+    /// target itself, in the target's group, or holds a Process capability
+    /// for it.
+    fn may_control_process(&self, caller: ProcessId, target: ProcessId) -> bool {
+        caller.0 == 1
+            || caller == target
+            || self
+                .pgid_of(caller)
+                .is_some_and(|pgid| self.pgid_of(target) == Some(pgid))
+            || self.has_kill_permission(caller, target)
+    }
+
+    /// Whether `caller` may bring processes into group `pgid`.
+    fn may_control_group(&self, caller: ProcessId, pgid: ProcessId) -> bool {
+        caller.0 == 1
+            || self.pgid_of(caller) == Some(pgid)
+            || self.has_kill_permission(caller, pgid)
+    }
+
+    /// Set `pid`'s group and return the ProcessGroupChanged commit.
+    fn move_to_group(&mut self, pid: ProcessId, pgid: ProcessId, timestamp: u64) -> Commit {
+        if let Some(proc) = self.processes.get_mut(&pid) {
+            proc.pgid = pgid;
+        }
+        Commit {
+            id: [0u8; 32],
+            prev_commit: [0u8; 32],
+            seq: 0,
+            timestamp,
+            commit_type: CommitType::ProcessGroupChanged {
+                pid: pid.0,
+                pgid: pgid.0,
+            },
+            caused_by: None,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Debugger inspection for KernelCore.
+//!
+//! A Debugger capability lets its holder read any process's runtime state
+//! and memory. The supervisor mints the only one into PermissionService,
+//! which grants it on to a debugger app once the user approves. Inspecting
+//! changes nothing, so only minting the capability is a commit.
+
+use alloc::vec::Vec;
+
+use crate::capability::{Capability, Permissions};
+use crate::error::KernelError;
+use crate::types::{CapSlot, ObjectType, Process, ProcessId};
+use zos_axiom::{Commit, CommitType};
+use zos_hal::{HalError, HAL};
+use zos_ipc::syscall::PROC_READ_MEM_MAX;
+
+use super::KernelCore;
+
+impl<H: HAL> KernelCore<H> {
+    /// Insert a Debugger capability with full permissions into `holder`'s
+    /// CSpace.
+    ///
+    /// Returns the capability's slot and its CapInserted commit.
+    pub fn create_debugger_cap(
+        &mut self,
+        holder: ProcessId,
+        timestamp: u64,
+    ) -> (Result<CapSlot, KernelError>, Vec<Commit>) {
+        if !self.processes.contains_key(&holder) {
+            return (Err(KernelError::ProcessNotFound), Vec::new());
+        }
+
+        let cap_id = self.next_cap_id();
+        let perms = Permissions::full();
+        let cap = Capability {
+            id: cap_id,
+            object_type: ObjectType::Debugger,
+            object_id: 0,
+            permissions: perms,
+            generation: 0,
+            expires_at: 0,
+        };
+        let slot = match self.cap_spaces.get_mut(&holder) {
+            Some(cspace) => cspace.insert(cap),
+            None => return (Err(KernelError::ProcessNotFound), Vec::new()),
+        };
+
+        self.hal.debug_write(&alloc::format!(
+            "[kernel] Created Debugger capability for PID {} at slot {}",
+            holder.0,
+            slot
+        ));
+
+        let commit = Commit {
+            id: [0u8; 32],
+            prev_commit: [0u8; 32],
+            seq: 0,
+            timestamp,
+            commit_type: CommitType::CapInserted {
+                pid: holder.0,
+                slot,
+                cap_id,
+                object_type: ObjectType::Debugger as u8,
+                object_id: 0,
+                perms: perms.to_byte(),
+            },
+            caused_by: None,
+        };
+        (Ok(slot), alloc::vec![commit])
+    }
+
+    /// The process `caller` wants to inspect, if it holds a Debugger
+    /// capability with read permission.
+    pub fn inspect_process(
+        &self,
+        caller: ProcessId,
+        target: ProcessId,
+    ) -> Result<&Process, KernelError> {
+        if !self.has_debugger_cap(caller) {
+            self.hal.debug_write(&alloc::format!(
+                "[kernel] Inspect denied: PID {} lacks a Debugger capability",
+                caller.0
+            ));
+            return Err(KernelError::PermissionDenied);
+        }
+        self.processes
+            .get(&target)
+            .ok_or(KernelError::ProcessNotFound)
+    }
+
+    /// Read `len` bytes of `target`'s memory at `addr` for `caller`.
+    ///
+    /// Fails with `Hal(InvalidArgument)` for more than `PROC_READ_MEM_MAX`
+    /// bytes or bytes past the end of the process's memory.
+    pub fn read_process_memory(
+        &self,
+        caller: ProcessId,
+        target: ProcessId,
+        addr: u32,
+        len: u32,
+    ) -> Result<Vec<u8>, KernelError> {
+        let proc = self.inspect_process(caller, target)?;
+        let in_bounds = (addr as usize)
+            .checked_add(len as usize)
+            .is_some_and(|end| end <= proc.metrics.memory_size);
+        if len > PROC_READ_MEM_MAX || !in_bounds {
+            return Err(KernelError::Hal(HalError::InvalidArgument));
+        }
+        self.hal
+            .read_process_memory(target.0, addr, len)
+            .map_err(KernelError::Hal)
+    }
+
+    /// Whether `pid` holds a Debugger capability with read permission.
+    fn has_debugger_cap(&self, pid: ProcessId) -> bool {
+        self.cap_spaces.get(&pid).is_some_and(|cspace| {
+            cspace
+                .slots
+                .values()
+                .any(|cap| cap.object_type == ObjectType::Debugger && cap.permissions.read)
+        })
+    }
+}
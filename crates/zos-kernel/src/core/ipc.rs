@@ -5,13 +5,45 @@
 //! - Receiving messages (with and without capability transfer)
 //! - Checking for pending messages
 //! - Direct process-to-process messaging (supervisor override)
+//! - Deadlines and correlation IDs propagated along with messages
+//! - Reply capabilities handed over with call requests (see `call`)
+//! - Fair ordering of contending senders and receivers
+//!
+//! # Deadlines
+//!
+//! Every message carries the sender's current deadline. Receiving a message
+//! makes its deadline the receiver's, so when a service calls further
+//! services while handling a request, those calls inherit the original
+//! client's deadline without the service doing anything. Messages whose
+//! deadline has passed are dropped at receive: nobody is waiting for the
+//! result any more.
+//!
+//! # Correlation IDs
+//!
+//! Messages carry the sender's current correlation ID the same way, and
+//! receiving one makes its ID the receiver's. A client sets an ID before
+//! sending a request; a service answering straight away echoes it without
+//! doing anything, and one answering later sets it again from what it kept
+//! of the request.
+//!
+//! # Fairness
+//!
+//! An endpoint holds at most `MAX_QUEUED_MESSAGES`. Senders that find it
+//! full, and receivers that find nothing for them, wait in the endpoint's
+//! `WaitQueue`s and are served in the order they started waiting: a free
+//! slot or a queued message goes to the longest waiter, and a newcomer never
+//! overtakes someone already in line. Messages themselves are delivered in
+//! send order. A waiter that exits, or does not retry within
+//! `WAITER_TIMEOUT_NS`, loses its place.
 
 use alloc::vec;
 use alloc::vec::Vec;
 
 use crate::axiom_check;
 use crate::error::KernelError;
-use crate::ipc::{Message, TransferredCap, MAX_CAPS_PER_MESSAGE, MAX_MESSAGE_SIZE};
+use crate::ipc::{
+    Message, TransferredCap, MAX_CAPS_PER_MESSAGE, MAX_MESSAGE_SIZE, MAX_QUEUED_MESSAGES,
+};
 use crate::types::{CapSlot, EndpointId, ObjectType, ProcessId};
 use crate::Permissions;
 use zos_axiom::{Commit, CommitType};
@@ -42,40 +74,21 @@ impl<H: HAL> KernelCore<H> {
             Ok(id) => id,
             Err(e) => return (Err(e), None),
         };
+        if let Err(e) = self.admit_sender(endpoint_id, from_pid, timestamp) {
+            return (Err(e), None);
+        }
 
-        let data_len = data.len();
-
-        // Queue message
         let message = Message {
             from: from_pid,
             tag,
             data,
             transferred_caps: vec![],
+            deadline: self.deadline(from_pid),
+            correlation_id: self.correlation_id(from_pid),
+            call: None,
+            broadcast: None,
         };
-
-        if let Err(e) = self.queue_message(endpoint_id, message) {
-            return (Err(e), None);
-        }
-
-        // Update metrics
-        self.update_send_metrics(from_pid, endpoint_id, data_len, timestamp);
-
-        // Create MessageSent commit
-        let commit = Commit {
-            id: [0u8; 32],
-            prev_commit: [0u8; 32],
-            seq: 0,
-            timestamp,
-            commit_type: CommitType::MessageSent {
-                from_pid: from_pid.0,
-                to_endpoint: endpoint_id.0,
-                tag,
-                size: data_len,
-            },
-            caused_by: None,
-        };
-
-        (Ok(()), Some(commit))
+        self.send_admitted(endpoint_id, message, timestamp)
     }
 
     /// Send IPC message with capability transfer.
@@ -116,6 +129,11 @@ impl<H: HAL> KernelCore<H> {
             return (Err(e), commits);
         }
 
+        // Wait for room before the caps leave the sender
+        if let Err(e) = self.admit_sender(endpoint_id, from_pid, timestamp) {
+            return (Err(e), commits);
+        }
+
         // Remove capabilities and build transfer list
         let (transferred_caps, cap_commits) =
             match self.remove_and_transfer_caps(from_pid, cap_slots, timestamp) {
@@ -132,6 +150,10 @@ impl<H: HAL> KernelCore<H> {
             tag,
             data,
             transferred_caps,
+            deadline: self.deadline(from_pid),
+            correlation_id: self.correlation_id(from_pid),
+            call: None,
+            broadcast: None,
         };
 
         if let Err(e) = self.queue_message(endpoint_id, message) {
@@ -160,16 +182,50 @@ impl<H: HAL> KernelCore<H> {
             .get_mut(&endpoint_id)
             .ok_or(KernelError::EndpointNotFound)?;
 
-        let msg = endpoint.pending_messages.pop_front();
+        // Skip messages whose sender has already given up
+        let mut expired = 0usize;
+        while endpoint
+            .pending_messages
+            .front()
+            .is_some_and(|m| m.deadline.is_some_and(|d| d <= timestamp))
+        {
+            endpoint.pending_messages.pop_front();
+            expired += 1;
+        }
 
-        // Update metrics
+        // Receivers already waiting get the queued messages first
+        let processes = &self.processes;
+        let receivers = &mut endpoint.waiting_receivers;
+        receivers.prune(timestamp, |p| processes.contains_key(&p));
+        let msg = if receivers.position(pid) < endpoint.pending_messages.len() {
+            receivers.leave(pid);
+            endpoint.pending_messages.pop_front()
+        } else {
+            receivers.wait(pid, timestamp);
+            None
+        };
+        endpoint.metrics.queue_depth = endpoint.pending_messages.len();
+        if expired > 0 {
+            self.hal.debug_write(&alloc::format!(
+                "[kernel] Dropped {} expired message(s) for PID {} on endpoint {}",
+                expired, pid.0, endpoint_id.0
+            ));
+        }
+
+        // Update metrics, and take on the message's deadline and correlation
+        // ID
         if let Some(ref m) = msg {
-            endpoint.metrics.queue_depth = endpoint.pending_messages.len();
             if let Some(receiver) = self.processes.get_mut(&pid) {
                 receiver.metrics.ipc_received += 1;
                 receiver.metrics.ipc_bytes_received += m.data.len() as u64;
                 receiver.metrics.last_active_ns = timestamp;
             }
+            self.set_deadline(pid, m.deadline);
+            self.set_correlation_id(pid, m.correlation_id);
+            // The request of a call comes with its reply capability
+            if let Some(call) = m.call {
+                self.calls.accept(m.from, call, pid);
+            }
         }
 
         Ok(msg)
@@ -225,12 +281,46 @@ impl<H: HAL> KernelCore<H> {
         Ok(!endpoint.pending_messages.is_empty())
     }
 
+    /// Deadline stamped on messages `pid` sends, if any.
+    pub fn deadline(&self, pid: ProcessId) -> Option<u64> {
+        self.deadlines.get(&pid).copied()
+    }
+
+    /// Set (or clear) the deadline stamped on messages `pid` sends.
+    pub fn set_deadline(&mut self, pid: ProcessId, deadline: Option<u64>) {
+        match deadline {
+            Some(deadline) if self.processes.contains_key(&pid) => {
+                self.deadlines.insert(pid, deadline);
+            }
+            _ => {
+                self.deadlines.remove(&pid);
+            }
+        }
+    }
+
+    /// Correlation ID stamped on messages `pid` sends, if any.
+    pub fn correlation_id(&self, pid: ProcessId) -> Option<u32> {
+        self.correlations.get(&pid).copied()
+    }
+
+    /// Set (or clear) the correlation ID stamped on messages `pid` sends.
+    pub fn set_correlation_id(&mut self, pid: ProcessId, correlation_id: Option<u32>) {
+        match correlation_id {
+            Some(id) if self.processes.contains_key(&pid) => {
+                self.correlations.insert(pid, id);
+            }
+            _ => {
+                self.correlations.remove(&pid);
+            }
+        }
+    }
+
     // ========================================================================
     // Private helper methods
     // ========================================================================
 
     /// Validate send capability using axiom_check
-    fn validate_send_cap(
+    pub(super) fn validate_send_cap(
         &self,
         from_pid: ProcessId,
         endpoint_slot: CapSlot,
@@ -276,7 +366,7 @@ impl<H: HAL> KernelCore<H> {
     }
 
     /// Validate receive capability using axiom_check
-    fn validate_receive_cap(
+    pub(super) fn validate_receive_cap(
         &self,
         pid: ProcessId,
         endpoint_slot: CapSlot,
@@ -401,6 +491,71 @@ impl<H: HAL> KernelCore<H> {
         Ok((installed_slots, commits))
     }
 
+    /// Let `pid` send to an endpoint if a free slot is its turn; otherwise
+    /// put it in line and fail with `WouldBlock`.
+    pub(super) fn admit_sender(
+        &mut self,
+        endpoint_id: EndpointId,
+        pid: ProcessId,
+        timestamp: u64,
+    ) -> Result<(), KernelError> {
+        let endpoint = self
+            .endpoints
+            .get_mut(&endpoint_id)
+            .ok_or(KernelError::EndpointNotFound)?;
+        // Broadcast endpoints only take published messages
+        if endpoint.is_broadcast() {
+            return Err(KernelError::PermissionDenied);
+        }
+
+        let processes = &self.processes;
+        let senders = &mut endpoint.blocked_senders;
+        senders.prune(timestamp, |p| processes.contains_key(&p));
+        let free = MAX_QUEUED_MESSAGES.saturating_sub(endpoint.pending_messages.len());
+        if senders.position(pid) < free {
+            senders.leave(pid);
+            Ok(())
+        } else {
+            senders.wait(pid, timestamp);
+            Err(KernelError::WouldBlock)
+        }
+    }
+
+    /// Queue a message from a sender already admitted to the endpoint.
+    ///
+    /// Returns the MessageSent commit on success.
+    pub(super) fn send_admitted(
+        &mut self,
+        endpoint_id: EndpointId,
+        message: Message,
+        timestamp: u64,
+    ) -> (Result<(), KernelError>, Option<Commit>) {
+        let (from_pid, tag, data_len) = (message.from, message.tag, message.data.len());
+        if let Err(e) = self.queue_message(endpoint_id, message) {
+            return (Err(e), None);
+        }
+
+        // Update metrics
+        self.update_send_metrics(from_pid, endpoint_id, data_len, timestamp);
+
+        // Create MessageSent commit
+        let commit = Commit {
+            id: [0u8; 32],
+            prev_commit: [0u8; 32],
+            seq: 0,
+            timestamp,
+            commit_type: CommitType::MessageSent {
+                from_pid: from_pid.0,
+                to_endpoint: endpoint_id.0,
+                tag,
+                size: data_len,
+            },
+            caused_by: None,
+        };
+
+        (Ok(()), Some(commit))
+    }
+
     /// Queue a message to an endpoint
     fn queue_message(
         &mut self,
@@ -417,7 +572,7 @@ impl<H: HAL> KernelCore<H> {
     }
 
     /// Update metrics after sending a message
-    fn update_send_metrics(
+    pub(super) fn update_send_metrics(
         &mut self,
         from_pid: ProcessId,
         endpoint_id: EndpointId,
@@ -429,6 +584,7 @@ impl<H: HAL> KernelCore<H> {
             endpoint.metrics.queue_depth = endpoint.pending_messages.len();
             endpoint.metrics.total_messages += 1;
             endpoint.metrics.total_bytes += data_len as u64;
+            *endpoint.metrics.sent_by.entry(from_pid).or_insert(0) += 1;
             if endpoint.metrics.queue_depth > endpoint.metrics.queue_high_water {
                 endpoint.metrics.queue_high_water = endpoint.metrics.queue_depth;
             }
@@ -4,25 +4,48 @@
 //! split into logical submodules:
 //!
 //! - `process` - Process lifecycle (register, kill, fault)
+//! - `group` - Process groups and group kills
 //! - `endpoint` - Endpoint management (create, list, get)
 //! - `capability` - Capability operations (grant, revoke, derive, delete)
 //! - `ipc` - IPC send/receive operations
+//! - `names` - Service names resolved to capabilities on first use
 //! - `syscall` - Syscall dispatch and handling
-
+//! - `timer` - Process timers and their delivery
+//! - `call` - Synchronous calls and their reply capabilities
+//! - `shm` - Shared memory regions and their mappings
+//! - `notification` - Notifications and their signal bits
+//! - `broadcast` - Broadcast endpoints, their subscribers and publishing
+//! - `graph` - Service graph snapshots
+//! - `inspect` - Debugger inspection of other processes
+
+mod broadcast;
+mod call;
 mod capability;
 mod endpoint;
+mod graph;
+mod group;
+mod inspect;
 mod ipc;
+pub mod names;
+mod notification;
 mod process;
+mod shm;
 mod syscall;
+mod timer;
 
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 
+use crate::call::CallTable;
 use crate::error::KernelError;
 use crate::ipc::Endpoint;
-use crate::types::{EndpointId, Process, ProcessId, SystemMetrics};
+use crate::notification::Notification;
+use crate::shm::ShmTable;
+use crate::timer::TimerWheel;
+use crate::types::{EndpointId, NotificationId, Process, ProcessId, SystemMetrics};
 use crate::{AxiomError, CapabilitySpace};
-use zos_hal::HAL;
+use zos_allocator::MessagePool;
+use zos_hal::{HalError, HAL};
 
 /// The kernel core holds all mutable state.
 ///
@@ -45,10 +68,29 @@ pub struct KernelCore<H: HAL> {
     pub(crate) next_pid: u64,
     /// Next endpoint ID
     pub(crate) next_endpoint_id: u64,
+    /// Notifications
+    pub(crate) notifications: BTreeMap<NotificationId, Notification>,
+    /// Next notification ID
+    pub(crate) next_notification_id: u64,
     /// Next capability ID
     pub(crate) next_cap_id: u64,
     /// Total IPC messages since boot
     pub(crate) total_ipc_count: u64,
+    /// Service names bound by init, and per-process resolutions
+    pub(crate) names: names::NameTable,
+    /// Deadline (uptime nanos) of the request each process is working on
+    pub(crate) deadlines: BTreeMap<ProcessId, u64>,
+    /// Correlation ID of the request each process is working on
+    pub(crate) correlations: BTreeMap<ProcessId, u32>,
+    /// Timers armed by processes
+    pub(crate) timers: TimerWheel,
+    /// Calls in progress, by caller
+    pub(crate) calls: CallTable,
+    /// Shared memory regions and mappings
+    pub(crate) shm: ShmTable,
+    /// Recycled message payload buffers. Not kernel state: it only saves
+    /// allocations and is left out of replay and state hashing.
+    pub(crate) message_pool: MessagePool,
 }
 
 impl<H: HAL> KernelCore<H> {
@@ -61,8 +103,17 @@ impl<H: HAL> KernelCore<H> {
             endpoints: BTreeMap::new(),
             next_pid: 1,
             next_endpoint_id: 1,
+            notifications: BTreeMap::new(),
+            next_notification_id: 1,
             next_cap_id: 1,
             total_ipc_count: 0,
+            names: names::NameTable::default(),
+            deadlines: BTreeMap::new(),
+            correlations: BTreeMap::new(),
+            timers: TimerWheel::default(),
+            calls: CallTable::default(),
+            shm: ShmTable::default(),
+            message_pool: MessagePool::new(),
         }
     }
 
@@ -124,6 +175,7 @@ impl<H: HAL> KernelCore<H> {
             total_pending_messages: self.total_pending_messages(),
             total_ipc_messages: self.total_ipc_count,
             uptime_ns,
+            message_pool: self.message_pool.stats(),
         }
     }
 
@@ -132,12 +184,19 @@ impl<H: HAL> KernelCore<H> {
     // ========================================================================
 
     /// Allocate memory to a process (simulated)
+    ///
+    /// Fails with `HalError::OutOfMemory` rather than grow the process past
+    /// its memory limit.
     pub fn allocate_memory(&mut self, pid: ProcessId, bytes: usize) -> Result<usize, KernelError> {
         let proc = self
             .processes
             .get_mut(&pid)
             .ok_or(KernelError::ProcessNotFound)?;
-        proc.metrics.memory_size += bytes;
+        let total = proc.metrics.memory_size.saturating_add(bytes);
+        if total > proc.memory_limit {
+            return Err(KernelError::Hal(HalError::OutOfMemory));
+        }
+        proc.metrics.memory_size = total;
         self.hal.debug_write(&alloc::format!(
             "[kernel] PID {} allocated {} bytes (total: {} bytes)",
             pid.0,
@@ -164,10 +223,12 @@ impl<H: HAL> KernelCore<H> {
     }
 
     /// Update process memory size (called when WASM memory grows)
-    pub fn update_process_memory(&mut self, pid: ProcessId, new_size: usize) {
-        if let Some(proc) = self.processes.get_mut(&pid) {
-            proc.metrics.memory_size = new_size;
-        }
+    ///
+    /// Returns the process's memory limit if the new size is over it.
+    pub fn update_process_memory(&mut self, pid: ProcessId, new_size: usize) -> Option<usize> {
+        let proc = self.processes.get_mut(&pid)?;
+        proc.metrics.memory_size = new_size;
+        (new_size > proc.memory_limit).then_some(proc.memory_limit)
     }
 }
 
@@ -0,0 +1,239 @@
+//! Named capability references for KernelCore.
+//!
+//! Init binds every registered service name to that service's input
+//! endpoint. A process can then address a service as `CapRef::Named("vfs")`
+//! instead of holding a capability granted at spawn: on first use the kernel
+//! resolves the name, inserts a send-only capability into the caller's
+//! CSpace and caches the slot.
+//!
+//! Each binding carries a generation. When init rebinds a name because the
+//! service restarted, cached slots for the old generation are stale; the
+//! next use deletes the stale capability and resolves again, so callers
+//! never see the restart.
+//!
+//! The bindings themselves are not part of the CommitLog. Only the
+//! capabilities created and deleted during resolution are, which is all
+//! replay needs to rebuild the CSpaces.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::KernelError;
+use crate::types::{CapSlot, EndpointId, ObjectType, ProcessId};
+use crate::Permissions;
+use zos_axiom::Commit;
+use zos_hal::HAL;
+use zos_ipc::pid::INIT;
+
+use super::KernelCore;
+
+/// Maximum bound names (Rule 11).
+pub const MAX_BOUND_NAMES: usize = 64;
+
+/// Maximum length of a bound name in bytes.
+pub const MAX_NAME_LEN: usize = 32;
+
+/// Maximum cached name resolutions per process.
+pub const MAX_RESOLVED_PER_PROCESS: usize = 32;
+
+/// Slot where every service holds the capability to its own input endpoint.
+const SERVICE_INPUT_SLOT: CapSlot = 1;
+
+/// How a process refers to an endpoint it sends to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CapRef {
+    /// A capability already in the caller's CSpace
+    Slot(CapSlot),
+    /// A service name, resolved by the kernel on first use
+    Named(String),
+}
+
+/// A service name bound by init.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NameBinding {
+    /// Process currently serving the name
+    pub pid: ProcessId,
+    /// Its input endpoint
+    pub endpoint: EndpointId,
+    /// Incremented on every (re)bind
+    pub generation: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Resolved {
+    slot: CapSlot,
+    generation: u64,
+}
+
+/// Bound names and per-process resolution caches.
+#[derive(Debug, Default)]
+pub struct NameTable {
+    bindings: BTreeMap<String, NameBinding>,
+    resolved: BTreeMap<ProcessId, BTreeMap<String, Resolved>>,
+    next_generation: u64,
+}
+
+impl NameTable {
+    /// Current binding for `name`.
+    pub fn get(&self, name: &str) -> Option<&NameBinding> {
+        self.bindings.get(name)
+    }
+
+    /// Every bound name, in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &NameBinding)> {
+        self.bindings.iter().map(|(name, binding)| (name.as_str(), binding))
+    }
+
+    /// Forget a process: its cache goes, and so do names it was serving.
+    pub(crate) fn forget_process(&mut self, pid: ProcessId) {
+        self.resolved.remove(&pid);
+        self.bindings.retain(|_, binding| binding.pid != pid);
+    }
+}
+
+impl<H: HAL> KernelCore<H> {
+    /// Bind `name` to `service_pid`'s input endpoint (Init only).
+    ///
+    /// Rebinding a name bumps its generation, invalidating every cached
+    /// resolution of it.
+    pub fn bind_name(
+        &mut self,
+        caller: ProcessId,
+        name: &str,
+        service_pid: ProcessId,
+    ) -> Result<NameBinding, KernelError> {
+        if caller.0 != INIT as u64 {
+            return Err(KernelError::PermissionDenied);
+        }
+        let is_new = !self.names.bindings.contains_key(name);
+        if name.is_empty()
+            || name.len() > MAX_NAME_LEN
+            || (is_new && self.names.bindings.len() >= MAX_BOUND_NAMES)
+        {
+            return Err(KernelError::PermissionDenied);
+        }
+
+        let cap = self
+            .cap_spaces
+            .get(&service_pid)
+            .ok_or(KernelError::ProcessNotFound)?
+            .get(SERVICE_INPUT_SLOT)
+            .ok_or(KernelError::InvalidCapability)?;
+        let endpoint = EndpointId(cap.object_id);
+        let owned = self
+            .endpoints
+            .get(&endpoint)
+            .is_some_and(|ep| ep.owner == service_pid);
+        if cap.object_type != ObjectType::Endpoint || !owned {
+            return Err(KernelError::EndpointNotFound);
+        }
+
+        self.names.next_generation += 1;
+        let binding = NameBinding {
+            pid: service_pid,
+            endpoint,
+            generation: self.names.next_generation,
+        };
+        self.names.bindings.insert(String::from(name), binding);
+        self.hal.debug_write(&alloc::format!(
+            "[kernel] Name '{}' bound to PID {} endpoint {} (generation {})",
+            name,
+            service_pid.0,
+            endpoint.0,
+            binding.generation
+        ));
+        Ok(binding)
+    }
+
+    /// Resolve a capability reference to a slot in `pid`'s CSpace.
+    ///
+    /// Named references are resolved through the bound names and cached;
+    /// returns the commits for any capability created or deleted.
+    pub fn resolve_cap_ref(
+        &mut self,
+        pid: ProcessId,
+        cap_ref: &CapRef,
+        timestamp: u64,
+    ) -> (Result<CapSlot, KernelError>, Vec<Commit>) {
+        let name = match cap_ref {
+            CapRef::Slot(slot) => return (Ok(*slot), Vec::new()),
+            CapRef::Named(name) => name,
+        };
+        let mut commits = Vec::new();
+
+        let binding = self.names.bindings.get(name.as_str()).copied();
+        let cached = self
+            .names
+            .resolved
+            .get(&pid)
+            .and_then(|cache| cache.get(name.as_str()))
+            .copied();
+        if let Some(cached) = cached {
+            if binding.is_some_and(|b| b.generation == cached.generation) {
+                return (Ok(cached.slot), commits);
+            }
+            // Stale: the service restarted (or is gone) since we resolved
+            let (_, deleted) = self.delete_capability(pid, cached.slot, timestamp);
+            commits.extend(deleted);
+            if let Some(cache) = self.names.resolved.get_mut(&pid) {
+                cache.remove(name.as_str());
+            }
+        }
+
+        let Some(binding) = binding else {
+            return (Err(KernelError::EndpointNotFound), commits);
+        };
+        let cache_len = self.names.resolved.get(&pid).map_or(0, |c| c.len());
+        if cache_len >= MAX_RESOLVED_PER_PROCESS {
+            return (Err(KernelError::PermissionDenied), commits);
+        }
+
+        let (result, granted) = self.grant_capability_to_endpoint(
+            binding.pid,
+            binding.endpoint,
+            pid,
+            Permissions::write_only(),
+            timestamp,
+        );
+        commits.extend(granted);
+        let slot = match result {
+            Ok(slot) => slot,
+            Err(e) => return (Err(e), commits),
+        };
+        let resolved = Resolved {
+            slot,
+            generation: binding.generation,
+        };
+        self.names
+            .resolved
+            .entry(pid)
+            .or_default()
+            .insert(name.clone(), resolved);
+        (Ok(slot), commits)
+    }
+
+    /// Send a message through a capability reference.
+    pub fn ipc_send_ref(
+        &mut self,
+        from_pid: ProcessId,
+        cap_ref: &CapRef,
+        tag: u32,
+        data: Vec<u8>,
+        timestamp: u64,
+    ) -> (Result<(), KernelError>, Vec<Commit>) {
+        let (slot, mut commits) = self.resolve_cap_ref(from_pid, cap_ref, timestamp);
+        let slot = match slot {
+            Ok(slot) => slot,
+            Err(e) => return (Err(e), commits),
+        };
+        let (result, sent) = self.ipc_send(from_pid, slot, tag, data, timestamp);
+        commits.extend(sent);
+        (result, commits)
+    }
+
+    /// Bound names and resolution caches.
+    pub fn names(&self) -> &NameTable {
+        &self.names
+    }
+}
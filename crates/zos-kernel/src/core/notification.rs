@@ -0,0 +1,217 @@
+//! Notifications for KernelCore.
+//!
+//! Creating a notification is a `NotificationCreated` commit followed by the
+//! `CapInserted` of its owner's capability; each signal is a
+//! `NotificationSignaled` commit. Waiting only takes the volatile pending
+//! bits, like receiving a message, so it is not a commit.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::capability::{axiom_check, Capability, Permissions};
+use crate::error::KernelError;
+use crate::notification::{Notification, MAX_NOTIFICATIONS_PER_PROCESS};
+use crate::types::{CapSlot, NotificationId, ObjectType, ProcessId};
+use zos_axiom::{Commit, CommitType};
+use zos_hal::HAL;
+
+use super::{map_axiom_error, KernelCore};
+
+impl<H: HAL> KernelCore<H> {
+    /// Create a notification owned by a process, with a full-permission
+    /// capability for it.
+    ///
+    /// Returns (Result<(NotificationId, CapSlot), KernelError>, Vec<Commit>).
+    pub fn create_notification(
+        &mut self,
+        owner: ProcessId,
+        timestamp: u64,
+    ) -> (Result<(NotificationId, CapSlot), KernelError>, Vec<Commit>) {
+        if !self.processes.contains_key(&owner) {
+            return (Err(KernelError::ProcessNotFound), Vec::new());
+        }
+        let owned = self
+            .notifications
+            .values()
+            .filter(|n| n.owner == owner)
+            .count();
+        if owned >= MAX_NOTIFICATIONS_PER_PROCESS {
+            return (Err(KernelError::PermissionDenied), Vec::new());
+        }
+
+        let id = NotificationId(self.next_notification_id);
+        let cap_id = self.next_cap_id();
+        let perms = Permissions::full();
+        let cap = Capability {
+            id: cap_id,
+            object_type: ObjectType::Notification,
+            object_id: id.0,
+            permissions: perms,
+            generation: 0,
+            expires_at: 0,
+        };
+        let slot = match self.cap_spaces.get_mut(&owner) {
+            Some(cspace) => cspace.insert(cap),
+            None => return (Err(KernelError::ProcessNotFound), Vec::new()),
+        };
+        self.next_notification_id += 1;
+        self.notifications.insert(id, Notification::new(id, owner));
+
+        self.hal.debug_write(&alloc::format!(
+            "[kernel] Created notification {} for PID {}, cap slot {}",
+            id.0,
+            owner.0,
+            slot
+        ));
+
+        let commits = vec![
+            Commit {
+                id: [0u8; 32],
+                prev_commit: [0u8; 32],
+                seq: 0,
+                timestamp,
+                commit_type: CommitType::NotificationCreated {
+                    id: id.0,
+                    owner: owner.0,
+                },
+                caused_by: None,
+            },
+            Commit {
+                id: [0u8; 32],
+                prev_commit: [0u8; 32],
+                seq: 0,
+                timestamp,
+                commit_type: CommitType::CapInserted {
+                    pid: owner.0,
+                    slot,
+                    cap_id,
+                    object_type: ObjectType::Notification as u8,
+                    object_id: id.0,
+                    perms: perms.to_byte(),
+                },
+                caused_by: None,
+            },
+        ];
+
+        (Ok((id, slot)), commits)
+    }
+
+    /// OR `bits` into the notification in `slot` (needs write permission).
+    ///
+    /// Returns (Result<(), KernelError>, Option<Commit>).
+    pub fn signal_notification(
+        &mut self,
+        pid: ProcessId,
+        slot: CapSlot,
+        bits: u32,
+        timestamp: u64,
+    ) -> (Result<(), KernelError>, Option<Commit>) {
+        let id = match self.validate_notification_cap(
+            pid,
+            slot,
+            &Permissions::write_only(),
+            timestamp,
+        ) {
+            Ok(id) => id,
+            Err(e) => return (Err(e), None),
+        };
+        let Some(notification) = self.notifications.get_mut(&id) else {
+            return (Err(KernelError::InvalidCapability), None);
+        };
+        notification.signal(bits);
+
+        let commit = Commit {
+            id: [0u8; 32],
+            prev_commit: [0u8; 32],
+            seq: 0,
+            timestamp,
+            commit_type: CommitType::NotificationSignaled {
+                from_pid: pid.0,
+                id: id.0,
+                bits,
+            },
+            caused_by: None,
+        };
+
+        (Ok(()), Some(commit))
+    }
+
+    /// Take the pending bits of the notification in `slot` (needs read
+    /// permission). Returns 0 if nothing has been signalled since the last
+    /// wait.
+    pub fn wait_notification(
+        &mut self,
+        pid: ProcessId,
+        slot: CapSlot,
+        timestamp: u64,
+    ) -> Result<u32, KernelError> {
+        let id = self.validate_notification_cap(pid, slot, &Permissions::read_only(), timestamp)?;
+        self.notifications
+            .get_mut(&id)
+            .map(Notification::take)
+            .ok_or(KernelError::InvalidCapability)
+    }
+
+    /// Get notification by ID
+    pub fn get_notification(&self, id: NotificationId) -> Option<&Notification> {
+        self.notifications.get(&id)
+    }
+
+    /// List all notifications
+    pub fn list_notifications(&self) -> Vec<&Notification> {
+        self.notifications.values().collect()
+    }
+
+    /// Destroy the notifications owned by a process and return destruction
+    /// commits.
+    pub(super) fn cleanup_process_notifications(
+        &mut self,
+        pid: ProcessId,
+        timestamp: u64,
+    ) -> Vec<Commit> {
+        let owned: Vec<_> = self
+            .notifications
+            .values()
+            .filter(|n| n.owner == pid)
+            .map(|n| n.id)
+            .collect();
+
+        owned
+            .into_iter()
+            .filter_map(|id| {
+                self.notifications.remove(&id).map(|_| Commit {
+                    id: [0u8; 32],
+                    prev_commit: [0u8; 32],
+                    seq: 0,
+                    timestamp,
+                    commit_type: CommitType::NotificationDestroyed { id: id.0 },
+                    caused_by: None,
+                })
+            })
+            .collect()
+    }
+
+    fn validate_notification_cap(
+        &self,
+        pid: ProcessId,
+        slot: CapSlot,
+        required: &Permissions,
+        timestamp: u64,
+    ) -> Result<NotificationId, KernelError> {
+        let cspace = self
+            .cap_spaces
+            .get(&pid)
+            .ok_or(KernelError::ProcessNotFound)?;
+
+        let cap = axiom_check(
+            cspace,
+            slot,
+            required,
+            Some(ObjectType::Notification),
+            timestamp,
+        )
+        .map_err(map_axiom_error)?;
+
+        Ok(NotificationId(cap.object_id))
+    }
+}
@@ -3,6 +3,7 @@
 //! This module contains methods for:
 //! - Registering new processes
 //! - Killing processes (with and without capability checks)
+//! - Setting scheduling priority and memory limits (with capability check)
 //! - Recording process faults
 
 use alloc::string::String;
@@ -10,10 +11,13 @@ use alloc::vec;
 use alloc::vec::Vec;
 
 use crate::error::KernelError;
-use crate::types::{ObjectType, Process, ProcessId, ProcessMetrics, ProcessState};
+use crate::types::{
+    ObjectType, Priority, Process, ProcessId, ProcessMetrics, ProcessState, DEFAULT_MEMORY_LIMIT,
+    KILLED_EXIT_CODE,
+};
 use crate::CapabilitySpace;
 use zos_axiom::{Commit, CommitType};
-use zos_hal::HAL;
+use zos_hal::{HalError, HAL};
 
 use super::KernelCore;
 
@@ -81,6 +85,9 @@ impl<H: HAL> KernelCore<H> {
             pid,
             name: String::from(name),
             state: ProcessState::Running,
+            priority: Priority::initial(pid),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            pgid: pid,
             metrics: ProcessMetrics {
                 memory_size: 0,
                 ipc_sent: 0,
@@ -90,6 +97,10 @@ impl<H: HAL> KernelCore<H> {
                 syscall_count: 0,
                 last_active_ns: timestamp,
                 start_time_ns: timestamp,
+                run_time_ns: 0,
+                wakeups: 0,
+                resumed_ns: None,
+                last_syscall: 0,
             },
         };
         self.processes.insert(pid, process);
@@ -156,6 +167,114 @@ impl<H: HAL> KernelCore<H> {
         (Ok(()), commits)
     }
 
+    /// Set a process's scheduling priority class with capability check.
+    ///
+    /// The caller needs a Process capability for the target with write
+    /// permission, as for a kill. Init may set any priority, and a process
+    /// may always lower its own: stepping aside needs no authority, taking
+    /// precedence over others does.
+    ///
+    /// Setting the class a process already has records nothing.
+    pub fn set_priority_with_cap_check(
+        &mut self,
+        caller: ProcessId,
+        target: ProcessId,
+        priority: Priority,
+        timestamp: u64,
+    ) -> (Result<(), KernelError>, Vec<Commit>) {
+        let current = match self.processes.get(&target) {
+            Some(proc) => proc.priority,
+            None => return (Err(KernelError::ProcessNotFound), Vec::new()),
+        };
+
+        // Init (PID 1) has implicit permission, as for kills
+        let lowering_own = caller == target && priority >= current;
+        if caller.0 != 1 && !lowering_own && !self.has_kill_permission(caller, target) {
+            self.hal.debug_write(&alloc::format!(
+                "[kernel] Set priority denied: PID {} lacks Process capability for PID {}",
+                caller.0,
+                target.0
+            ));
+            return (Err(KernelError::PermissionDenied), Vec::new());
+        }
+
+        if priority == current {
+            return (Ok(()), Vec::new());
+        }
+        if let Some(proc) = self.processes.get_mut(&target) {
+            proc.priority = priority;
+        }
+        self.hal.set_process_priority(target.0, priority.as_u8());
+
+        let commit = Commit {
+            id: [0u8; 32],
+            prev_commit: [0u8; 32],
+            seq: 0,
+            timestamp,
+            commit_type: CommitType::ProcessPriorityChanged {
+                pid: target.0,
+                priority: priority.as_u8(),
+            },
+            caused_by: None,
+        };
+        (Ok(()), vec![commit])
+    }
+
+    /// Set a process's memory limit with capability check.
+    ///
+    /// Authority is as for [`Self::set_priority_with_cap_check`]: Init may
+    /// set any limit and a process may always lower its own, anything else
+    /// needs a Process capability for the target with write permission. A
+    /// limit below the memory the process already has is rejected.
+    ///
+    /// Setting the limit a process already has records nothing.
+    pub fn set_memory_limit_with_cap_check(
+        &mut self,
+        caller: ProcessId,
+        target: ProcessId,
+        limit: usize,
+        timestamp: u64,
+    ) -> (Result<(), KernelError>, Vec<Commit>) {
+        let (current, memory_size) = match self.processes.get(&target) {
+            Some(proc) => (proc.memory_limit, proc.metrics.memory_size),
+            None => return (Err(KernelError::ProcessNotFound), Vec::new()),
+        };
+
+        // Init (PID 1) has implicit permission, as for kills
+        let lowering_own = caller == target && limit <= current;
+        if caller.0 != 1 && !lowering_own && !self.has_kill_permission(caller, target) {
+            self.hal.debug_write(&alloc::format!(
+                "[kernel] Set memory limit denied: PID {} lacks Process capability for PID {}",
+                caller.0,
+                target.0
+            ));
+            return (Err(KernelError::PermissionDenied), Vec::new());
+        }
+        if limit < memory_size {
+            return (Err(KernelError::Hal(HalError::InvalidArgument)), Vec::new());
+        }
+
+        if limit == current {
+            return (Ok(()), Vec::new());
+        }
+        if let Some(proc) = self.processes.get_mut(&target) {
+            proc.memory_limit = limit;
+        }
+
+        let commit = Commit {
+            id: [0u8; 32],
+            prev_commit: [0u8; 32],
+            seq: 0,
+            timestamp,
+            commit_type: CommitType::ProcessMemoryLimitChanged {
+                pid: target.0,
+                limit: limit as u64,
+            },
+            caused_by: None,
+        };
+        (Ok(()), vec![commit])
+    }
+
     /// Kill a process and clean up its resources.
     ///
     /// Returns Vec<Commit> describing the mutations.
@@ -177,18 +296,38 @@ impl<H: HAL> KernelCore<H> {
                 timestamp,
                 commit_type: CommitType::ProcessExited {
                     pid: pid.0,
-                    code: -1,
+                    code: KILLED_EXIT_CODE,
                 },
                 caused_by: None,
             });
         }
 
-        // Remove its capability space
+        // Remove its capability space, name resolutions, bound names,
+        // deadline, correlation ID, timers and call
         self.cap_spaces.remove(&pid);
+        self.names.forget_process(pid);
+        self.deadlines.remove(&pid);
+        self.correlations.remove(&pid);
+        self.timers.cancel_all(pid);
+        self.calls.finish(pid);
 
         // Remove endpoints owned by this process and create destruction commits
         commits.extend(self.cleanup_process_endpoints(pid, timestamp));
 
+        // Drop its per-sender counts from the endpoints that remain
+        for endpoint in self.endpoints.values_mut() {
+            endpoint.metrics.sent_by.remove(&pid);
+        }
+
+        // Cancel its subscriptions, and those of the endpoints just removed
+        self.release_process_subscriptions(pid);
+
+        // Destroy its notifications
+        commits.extend(self.cleanup_process_notifications(pid, timestamp));
+
+        // Unmap its shared memory; regions it alone referred to are reaped
+        self.release_process_shm(pid);
+
         commits
     }
 
@@ -204,6 +343,7 @@ impl<H: HAL> KernelCore<H> {
     /// - 3: Capability violation
     /// - 4: Panic / abort
     /// - 5: Timeout / watchdog
+    /// - 6: Out of memory (`FAULT_OUT_OF_MEMORY`)
     /// - 0xFF: Unknown / unspecified
     pub fn fault_process(
         &mut self,
@@ -253,6 +393,9 @@ impl<H: HAL> KernelCore<H> {
             pid,
             name: String::from(name),
             state: ProcessState::Running,
+            priority: Priority::initial(pid),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            pgid: pid,
             metrics: ProcessMetrics {
                 memory_size: 65536, // Initial 64KB (1 WASM page)
                 ipc_sent: 0,
@@ -262,6 +405,10 @@ impl<H: HAL> KernelCore<H> {
                 syscall_count: 0,
                 last_active_ns: timestamp,
                 start_time_ns: timestamp,
+                run_time_ns: 0,
+                wakeups: 0,
+                resumed_ns: None,
+                last_syscall: 0,
             },
         }
     }
@@ -288,8 +435,9 @@ impl<H: HAL> KernelCore<H> {
         }
     }
 
-    /// Check if caller has permission to kill target process
-    fn has_kill_permission(&self, caller: ProcessId, target: ProcessId) -> bool {
+    /// Check if caller has permission to kill target process (or set its
+    /// priority, memory limit or group)
+    pub(super) fn has_kill_permission(&self, caller: ProcessId, target: ProcessId) -> bool {
         self.cap_spaces.get(&caller).is_some_and(|cspace| {
             cspace.slots.values().any(|cap| {
                 cap.object_type == ObjectType::Process
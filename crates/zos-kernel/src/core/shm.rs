@@ -0,0 +1,184 @@
+//! Shared memory for KernelCore.
+//!
+//! Creating a region inserts its Memory capability: a `CapInserted` commit
+//! like any other capability. Regions and mappings are kernel state like
+//! timers, so mapping, unmapping and reaping are not commits.
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use crate::capability::{axiom_check, Capability, Permissions};
+use crate::error::KernelError;
+use crate::shm::{ShmAccess, ShmError, ShmId, ShmMapping, ShmRegion};
+use crate::types::{CapSlot, ObjectType, ProcessId};
+use zos_axiom::{Commit, CommitType};
+use zos_hal::HAL;
+
+use super::{map_axiom_error, KernelCore};
+
+impl<H: HAL> KernelCore<H> {
+    /// Create a region of `size` bytes and give `pid` a Memory capability
+    /// for it with full permissions.
+    ///
+    /// Returns the capability's slot and its CapInserted commit.
+    pub fn shm_create(
+        &mut self,
+        pid: ProcessId,
+        size: u32,
+        timestamp: u64,
+    ) -> (Result<CapSlot, ShmError>, Vec<Commit>) {
+        if !self.cap_spaces.contains_key(&pid) {
+            return (
+                Err(ShmError::Capability(KernelError::ProcessNotFound)),
+                Vec::new(),
+            );
+        }
+        // Regions dropped since the last reap do not count against the limit
+        self.reap_shm();
+        let region = match self.shm.create(pid, size) {
+            Ok(id) => id,
+            Err(e) => return (Err(e), Vec::new()),
+        };
+
+        let cap_id = self.next_cap_id();
+        let perms = Permissions::full();
+        let cap = Capability {
+            id: cap_id,
+            object_type: ObjectType::Memory,
+            object_id: region,
+            permissions: perms,
+            generation: 0,
+            expires_at: 0,
+        };
+        let slot = match self.cap_spaces.get_mut(&pid) {
+            Some(cspace) => cspace.insert(cap),
+            None => return (Err(ShmError::NoRegion), Vec::new()),
+        };
+
+        self.hal.debug_write(&alloc::format!(
+            "[kernel] PID {} created shared memory region {} ({} bytes) at slot {}",
+            pid.0,
+            region,
+            size,
+            slot
+        ));
+
+        let commit = Commit {
+            id: [0u8; 32],
+            prev_commit: [0u8; 32],
+            seq: 0,
+            timestamp,
+            commit_type: CommitType::CapInserted {
+                pid: pid.0,
+                slot,
+                cap_id,
+                object_type: ObjectType::Memory as u8,
+                object_id: region,
+                perms: perms.to_byte(),
+            },
+            caused_by: None,
+        };
+        (Ok(slot), alloc::vec![commit])
+    }
+
+    /// Map the region of the Memory capability in `slot` at `addr` in
+    /// `pid`'s memory, up to `len` bytes.
+    ///
+    /// Needs read permission; the mapping is writable if the capability has
+    /// write permission too. Returns the mapped length.
+    pub fn shm_map(
+        &mut self,
+        pid: ProcessId,
+        slot: CapSlot,
+        addr: u32,
+        len: u32,
+        timestamp: u64,
+    ) -> Result<u32, ShmError> {
+        let cspace = self
+            .cap_spaces
+            .get(&pid)
+            .ok_or(ShmError::Capability(KernelError::ProcessNotFound))?;
+        let cap = axiom_check(
+            cspace,
+            slot,
+            &Permissions::read_only(),
+            Some(ObjectType::Memory),
+            timestamp,
+        )
+        .map_err(|e| ShmError::Capability(map_axiom_error(e)))?;
+        let (region, writable) = (cap.object_id, cap.permissions.write);
+        self.shm.map(pid, region, addr, len, writable)
+    }
+
+    /// Remove `pid`'s mapping at `addr`, reaping its region if nothing else
+    /// refers to it.
+    pub fn shm_unmap(&mut self, pid: ProcessId, addr: u32) -> Result<(), ShmError> {
+        self.shm.unmap(pid, addr).ok_or(ShmError::NotMapped)?;
+        self.reap_shm();
+        Ok(())
+    }
+
+    /// Check a copy of `len` bytes at `offset` between `pid`'s mapping at
+    /// `addr` and its region: into the region if `flush`, out of it
+    /// otherwise. The HAL does the copy once it is allowed.
+    pub fn shm_access(
+        &self,
+        pid: ProcessId,
+        addr: u32,
+        offset: u32,
+        len: u32,
+        flush: bool,
+    ) -> Result<ShmAccess, ShmError> {
+        self.shm.access(pid, addr, offset, len, flush)
+    }
+
+    /// `pid`'s mapping at `addr`.
+    pub fn shm_mapping(&self, pid: ProcessId, addr: u32) -> Option<&ShmMapping> {
+        self.shm.mapping(pid, addr)
+    }
+
+    /// A live region by ID.
+    pub fn shm_region(&self, id: ShmId) -> Option<&ShmRegion> {
+        self.shm.region(id)
+    }
+
+    /// Every live region.
+    pub fn shm_regions(&self) -> Vec<ShmRegion> {
+        self.shm.regions().copied().collect()
+    }
+
+    /// Drop `pid`'s mappings and reap the regions left behind (called once
+    /// its capabilities and endpoints are gone).
+    pub(super) fn release_process_shm(&mut self, pid: ProcessId) {
+        self.shm.unmap_all(pid);
+        self.reap_shm();
+    }
+
+    /// Reap every region no mapping and no Memory capability refers to,
+    /// counting capabilities in flight in queued messages.
+    fn reap_shm(&mut self) {
+        if self.shm.is_empty() {
+            return;
+        }
+        let held = self
+            .cap_spaces
+            .values()
+            .flat_map(|cspace| cspace.slots.iter().map(|(_, cap)| cap));
+        let queued = self
+            .endpoints
+            .values()
+            .flat_map(|e| e.pending_messages.iter())
+            .flat_map(|m| m.transferred_caps.iter().map(|t| &t.capability));
+        let referenced: BTreeSet<ShmId> = held
+            .chain(queued)
+            .filter(|cap| cap.object_type == ObjectType::Memory)
+            .map(|cap| cap.object_id)
+            .collect();
+        for region in self.shm.reap(&referenced) {
+            self.hal.debug_write(&alloc::format!(
+                "[kernel] Reaped shared memory region {}",
+                region
+            ));
+        }
+    }
+}
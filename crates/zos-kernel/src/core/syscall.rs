@@ -5,12 +5,16 @@
 use alloc::vec;
 use alloc::vec::Vec;
 
+use crate::call::CallError;
 use crate::error::KernelError;
-use crate::syscall::{CapInfo, Syscall, SyscallResult};
+use crate::syscall::{
+    CapInfo, Syscall, SyscallResult, SYS_CALL_WAIT, SYS_RECV, SYS_WAIT, SYS_YIELD,
+};
 use crate::types::{ProcessId, ProcessState};
 use zos_axiom::{Commit, CommitType};
 use zos_hal::HAL;
 
+use super::names::CapRef;
 use super::KernelCore;
 
 impl<H: HAL> KernelCore<H> {
@@ -50,11 +54,17 @@ impl<H: HAL> KernelCore<H> {
             } => {
                 self.handle_send_with_caps(from_pid, endpoint_slot, tag, data, cap_slots, timestamp)
             }
+            Syscall::SendNamed { name, tag, data } => {
+                self.handle_send_named(from_pid, name, tag, data, timestamp)
+            }
             Syscall::Call {
                 endpoint_slot,
                 tag,
                 data,
             } => self.handle_call(from_pid, endpoint_slot, tag, data, timestamp),
+            Syscall::Reply { caller, tag, data } => {
+                self.handle_reply(from_pid, caller, tag, data, timestamp)
+            }
 
             // Capability syscalls
             Syscall::ListCaps => self.handle_list_caps(from_pid),
@@ -136,6 +146,23 @@ impl<H: HAL> KernelCore<H> {
         (syscall_result, commits)
     }
 
+    fn handle_send_named(
+        &mut self,
+        from_pid: ProcessId,
+        name: alloc::string::String,
+        tag: u32,
+        data: Vec<u8>,
+        timestamp: u64,
+    ) -> (SyscallResult, Vec<Commit>) {
+        let cap_ref = CapRef::Named(name);
+        let (result, commits) = self.ipc_send_ref(from_pid, &cap_ref, tag, data, timestamp);
+        let syscall_result = match result {
+            Ok(()) => SyscallResult::Ok(0),
+            Err(e) => SyscallResult::Err(e),
+        };
+        (syscall_result, commits)
+    }
+
     fn handle_receive(
         &mut self,
         from_pid: ProcessId,
@@ -177,21 +204,36 @@ impl<H: HAL> KernelCore<H> {
         data: Vec<u8>,
         timestamp: u64,
     ) -> (SyscallResult, Vec<Commit>) {
-        // Call = send + block for reply
-        let (result, commit) = self.ipc_send(from_pid, endpoint_slot, tag, data, timestamp);
+        // Call = send + block for reply (collected with call_wait)
+        let (result, commit) = self.ipc_call(from_pid, endpoint_slot, tag, data, None, timestamp);
         let commits = commit.into_iter().collect();
         let syscall_result = match result {
-            Ok(()) => SyscallResult::WouldBlock,
-            Err(e) => SyscallResult::Err(e),
+            Ok(_) => SyscallResult::WouldBlock,
+            Err(CallError::Send(e)) => SyscallResult::Err(e),
+            Err(_) => SyscallResult::Err(KernelError::WouldBlock),
         };
         (syscall_result, commits)
     }
 
+    fn handle_reply(
+        &mut self,
+        from_pid: ProcessId,
+        caller: ProcessId,
+        tag: u32,
+        data: Vec<u8>,
+        timestamp: u64,
+    ) -> (SyscallResult, Vec<Commit>) {
+        match self.ipc_reply(from_pid, caller, tag, data, timestamp) {
+            Ok(()) => (SyscallResult::Ok(0), vec![]),
+            Err(e) => (SyscallResult::Err(e), vec![]),
+        }
+    }
+
     // ========================================================================
     // Capability syscalls
     // ========================================================================
 
-    fn handle_list_caps(&self, from_pid: ProcessId) -> (SyscallResult, Vec<Commit>) {
+    pub(crate) fn handle_list_caps(&self, from_pid: ProcessId) -> (SyscallResult, Vec<Commit>) {
         let caps = self
             .cap_spaces
             .get(&from_pid)
@@ -333,4 +375,51 @@ impl<H: HAL> KernelCore<H> {
             proc.metrics.last_active_ns = timestamp;
         }
     }
+
+    /// Account a syscall that entered the gateway at `entered` and left it
+    /// at `returned` with `result`.
+    ///
+    /// The time from the previous syscall returning to this one is run
+    /// time, unless the previous one left the process waiting: it yielded,
+    /// or polled for something that was not there, so it was idle or
+    /// descheduled. A syscall that ends a wait counts as a wakeup.
+    pub(crate) fn account_syscall(
+        &mut self,
+        from_pid: ProcessId,
+        syscall_num: u32,
+        result: i64,
+        entered: u64,
+        returned: u64,
+    ) {
+        let Some(proc) = self.processes.get_mut(&from_pid) else {
+            return;
+        };
+        let metrics = &mut proc.metrics;
+        let was_waiting = metrics.resumed_ns.is_none() && metrics.syscall_count > 0;
+        if let Some(resumed) = metrics.resumed_ns {
+            metrics.run_time_ns += entered.saturating_sub(resumed);
+        }
+        metrics.syscall_count += 1;
+        metrics.last_active_ns = entered;
+        metrics.last_syscall = syscall_num;
+
+        if leaves_waiting(syscall_num, result) {
+            metrics.resumed_ns = None;
+        } else {
+            if was_waiting {
+                metrics.wakeups += 1;
+            }
+            metrics.resumed_ns = Some(returned);
+        }
+    }
+}
+
+/// Whether a syscall that returned `result` left its caller waiting.
+fn leaves_waiting(syscall_num: u32, result: i64) -> bool {
+    match syscall_num {
+        SYS_YIELD => true,
+        // Nothing to receive, no signals pending, or no reply yet
+        SYS_RECV | SYS_WAIT | SYS_CALL_WAIT => result == 0,
+        _ => false,
+    }
 }
@@ -0,0 +1,96 @@
+//! Process timers for KernelCore.
+//!
+//! Timers are kernel state like deadlines: arming and cancelling one is not
+//! a commit. Each firing is, as the `MessageSent` (from PID 0) of the
+//! `MSG_TIMER_FIRED` it queues.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::error::KernelError;
+use crate::ipc::{Message, MAX_QUEUED_MESSAGES};
+use crate::timer::{TimerError, TimerId};
+use crate::types::{CapSlot, ProcessId};
+use zos_axiom::{Commit, CommitType};
+use zos_hal::HAL;
+use zos_ipc::kernel::MSG_TIMER_FIRED;
+
+use super::KernelCore;
+
+impl<H: HAL> KernelCore<H> {
+    /// Arm a timer for `pid` that fires on the endpoint in `endpoint_slot`
+    /// (which `pid` must be able to receive on) after `delay_nanos`, then
+    /// every `period_nanos` if given.
+    pub fn create_timer(
+        &mut self,
+        pid: ProcessId,
+        endpoint_slot: CapSlot,
+        delay_nanos: u64,
+        period_nanos: Option<u64>,
+        timestamp: u64,
+    ) -> Result<TimerId, KernelError> {
+        let endpoint = self.validate_receive_cap(pid, endpoint_slot, timestamp)?;
+        self.timers
+            .insert(pid, endpoint, timestamp, delay_nanos, period_nanos)
+            .map_err(|TimerError::TooManyTimers| KernelError::PermissionDenied)
+    }
+
+    /// Cancel one of `pid`'s timers. Returns whether it had one by that ID.
+    pub fn cancel_timer(&mut self, pid: ProcessId, id: TimerId) -> bool {
+        self.timers.cancel(pid, id)
+    }
+
+    /// Queue `MSG_TIMER_FIRED` for every timer due at `timestamp`.
+    ///
+    /// Returns a `MessageSent` commit per message queued. A timer whose
+    /// endpoint is gone is cancelled.
+    pub fn fire_timers(&mut self, timestamp: u64) -> Vec<Commit> {
+        let mut commits = Vec::new();
+        for timer in self.timers.expire(timestamp) {
+            let payload = timer.id.to_le_bytes();
+            let Some(endpoint) = self.endpoints.get_mut(&timer.endpoint) else {
+                self.timers.cancel(timer.owner, timer.id);
+                continue;
+            };
+            let still_queued = endpoint
+                .pending_messages
+                .iter()
+                .any(|m| m.from.0 == 0 && m.tag == MSG_TIMER_FIRED && m.data == payload);
+            if still_queued || endpoint.pending_messages.len() >= MAX_QUEUED_MESSAGES {
+                continue;
+            }
+
+            endpoint.pending_messages.push_back(Message {
+                from: ProcessId(0),
+                tag: MSG_TIMER_FIRED,
+                data: payload.to_vec(),
+                transferred_caps: vec![],
+                deadline: None,
+                correlation_id: None,
+                call: None,
+                broadcast: None,
+            });
+            self.update_send_metrics(ProcessId(0), timer.endpoint, payload.len(), timestamp);
+
+            commits.push(Commit {
+                id: [0u8; 32],
+                prev_commit: [0u8; 32],
+                seq: 0,
+                timestamp,
+                commit_type: CommitType::MessageSent {
+                    from_pid: 0,
+                    to_endpoint: timer.endpoint.0,
+                    tag: MSG_TIMER_FIRED,
+                    size: payload.len(),
+                },
+                caused_by: None,
+            });
+        }
+        commits
+    }
+
+    /// Number of timers `pid` has armed.
+    pub fn timer_count(&self, pid: ProcessId) -> usize {
+        self.timers.count_of(pid)
+    }
+}
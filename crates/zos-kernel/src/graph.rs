@@ -0,0 +1,62 @@
+//! Service graph
+//!
+//! A snapshot of how the running system is wired, for system-map style
+//! DevTools: every live process with the service names bound to it and the
+//! endpoints it owns, and an edge from each process to every other process
+//! it can reach. A process reaches another by holding capabilities to the
+//! other's endpoints, notifications, shared memory or the process itself,
+//! or by having sent messages to the other's endpoints.
+//!
+//! Edges are worth reading in both directions: messages without
+//! capabilities is traffic over authority since deleted or revoked, and
+//! capabilities without messages is authority never used.
+//!
+//! With the `serde` feature the graph serializes as-is.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::types::{EndpointId, ProcessId};
+
+/// Processes and the edges between them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ServiceGraph {
+    /// By PID
+    pub nodes: Vec<ServiceNode>,
+    /// By (from, to)
+    pub edges: Vec<ServiceEdge>,
+}
+
+impl ServiceGraph {
+    /// The edge from `from` to `to`, if there is one.
+    pub fn edge(&self, from: ProcessId, to: ProcessId) -> Option<&ServiceEdge> {
+        self.edges.iter().find(|e| e.from == from && e.to == to)
+    }
+}
+
+/// A live process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ServiceNode {
+    pub pid: ProcessId,
+    pub name: String,
+    /// Service names bound to the process
+    pub services: Vec<String>,
+    /// Endpoints it owns
+    pub endpoints: Vec<EndpointId>,
+}
+
+/// How one process reaches another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ServiceEdge {
+    /// Capability holder and sender
+    pub from: ProcessId,
+    /// Owner of what `from` holds or sends to
+    pub to: ProcessId,
+    /// Capabilities `from` holds to `to`'s objects
+    pub capabilities: u32,
+    /// Messages `from` has sent to `to`'s live endpoints
+    pub messages: u64,
+}
@@ -0,0 +1,75 @@
+//! Runtime invariant checks (`debug-invariants` feature)
+//!
+//! Debug builds can check the architectural invariants of
+//! docs/invariants/invariants.md as they run, instead of finding out from a
+//! replay that diverges much later. With the feature enabled the System
+//! checks:
+//!
+//! - **No direct kernel access** (Invariants 1, 16): when a syscall enters
+//!   the gateway, the state digest still matches the live state, so nothing
+//!   has mutated KernelCore since the last commit
+//! - **Mutations are commits** (Invariant 2): when a syscall leaves the
+//!   gateway, everything it changed was recorded
+//! - **Lifecycle ops flow through Init**: only Init's syscalls create
+//!   processes or endpoints for other processes
+//! - **Every grant is SysLogged** (Invariant 3): a `CapGranted` commit is
+//!   recorded under a SysLog request from the granting process, or from
+//!   the supervisor for the direct grants made at spawn
+//!
+//! A violation is written to the debug log as an [`InvariantViolation`]
+//! report and then panics, at the moment it happens.
+
+use alloc::string::String;
+use core::fmt;
+
+use crate::types::ProcessId;
+
+/// An architectural invariant checked at runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Invariant {
+    /// Kernel state changed outside the gateway
+    NoDirectKernelAccess,
+    /// A syscall changed kernel state without a commit
+    MutationsAreCommits,
+    /// A lifecycle operation came from a process other than Init
+    LifecycleThroughInit,
+    /// A capability grant has no SysLog request behind it
+    GrantsAreSysLogged,
+}
+
+impl Invariant {
+    /// Short name used in reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Invariant::NoDirectKernelAccess => "no-direct-kernel-access",
+            Invariant::MutationsAreCommits => "mutations-are-commits",
+            Invariant::LifecycleThroughInit => "lifecycle-through-init",
+            Invariant::GrantsAreSysLogged => "grants-are-syslogged",
+        }
+    }
+}
+
+/// Where and how an invariant was violated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvariantViolation {
+    pub invariant: Invariant,
+    /// Sender of the syscall in flight, if any
+    pub sender: Option<ProcessId>,
+    /// Syscall in flight, if any
+    pub syscall_num: Option<u32>,
+    /// Sequence number of the last commit recorded before the violation
+    pub commit_seq: u64,
+    /// What was seen
+    pub detail: String,
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invariant {} violated", self.invariant.name())?;
+        match (self.sender, self.syscall_num) {
+            (Some(pid), Some(num)) => write!(f, " in syscall {:#x} from PID {}", num, pid.0)?,
+            _ => write!(f, " outside any syscall")?,
+        }
+        write!(f, " after commit {}: {}", self.commit_seq, self.detail)
+    }
+}
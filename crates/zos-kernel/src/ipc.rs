@@ -3,12 +3,29 @@
 //! This module contains types for IPC messaging:
 //! - Messages and transferred capabilities
 //! - Endpoints and their metrics
+//! - Wait queues that order contending senders and receivers
+//! - Broadcast endpoints and their subscribers
 //! - IPC traffic monitoring
+//!
+//! # Broadcast endpoints
+//!
+//! A broadcast endpoint queues nothing itself. Holders of its capability
+//! with read permission subscribe one of their own endpoints to it
+//! (`SYS_SUBSCRIBE`); holders with write permission publish to it
+//! (`SYS_PUBLISH`), which queues a copy of the message on every subscribed
+//! endpoint, where it is received like any other message. A subscriber
+//! that has fallen `MAX_BROADCAST_BACKLOG` messages behind, or whose
+//! endpoint is full, misses the message instead of holding up the
+//! publisher or the other subscribers; its `dropped` count says how many
+//! it missed. A subscription ends when it is cancelled, when its endpoint
+//! goes away, or when the broadcast endpoint does.
 
 use alloc::collections::VecDeque;
 use alloc::vec::Vec;
 
+use crate::call::CallId;
 use crate::capability::Capability;
+use crate::error::KernelError;
 use crate::types::{EndpointId, EndpointMetrics, ProcessId};
 use zos_axiom::CapSlot;
 
@@ -19,6 +36,24 @@ pub const MAX_CAPS_PER_MESSAGE: usize = 8;
 /// Sized to support large IPC responses (e.g., PQ hybrid keys ~6KB)
 pub const MAX_MESSAGE_SIZE: usize = 16384;
 
+/// Maximum messages queued on one endpoint. A send to a full endpoint
+/// fails with `WouldBlock` and puts the sender in line for the next free slot.
+pub const MAX_QUEUED_MESSAGES: usize = 256;
+
+/// How long a waiter keeps its place in line without retrying. A process
+/// that gave up (or stopped polling) must not hold its turn forever.
+pub const WAITER_TIMEOUT_NS: u64 = 1_000_000_000;
+
+/// Maximum subscribers of one broadcast endpoint
+pub const MAX_BROADCAST_SUBSCRIBERS: usize = 64;
+
+/// Messages of one broadcast a subscriber may have queued before it starts
+/// missing new ones
+pub const MAX_BROADCAST_BACKLOG: usize = 32;
+
+/// Maximum broadcast endpoints owned by one process (DoS protection)
+pub const MAX_BROADCASTS_PER_PROCESS: usize = 8;
+
 /// A capability being transferred via IPC.
 ///
 /// When a capability is transferred, it is moved from the sender's CSpace
@@ -42,6 +77,15 @@ pub struct Message {
     pub data: Vec<u8>,
     /// Capabilities transferred with this message
     pub transferred_caps: Vec<TransferredCap>,
+    /// Uptime (nanos) after which the sender no longer wants this handled
+    pub deadline: Option<u64>,
+    /// ID pairing a response with the request it answers
+    pub correlation_id: Option<u32>,
+    /// Call this is the request of; receiving it hands the receiver the
+    /// call's reply capability
+    pub call: Option<CallId>,
+    /// Broadcast endpoint this is a published copy from
+    pub broadcast: Option<EndpointId>,
 }
 
 /// IPC endpoint
@@ -52,8 +96,198 @@ pub struct Endpoint {
     pub owner: ProcessId,
     /// Queue of pending messages
     pub pending_messages: VecDeque<Message>,
+    /// Senders waiting for room in a full queue
+    pub blocked_senders: WaitQueue,
+    /// Receivers waiting for a message
+    pub waiting_receivers: WaitQueue,
     /// Endpoint metrics
     pub metrics: EndpointMetrics,
+    /// Subscribers, if this is a broadcast endpoint
+    pub broadcast: Option<Subscribers>,
+}
+
+impl Endpoint {
+    /// An empty endpoint
+    pub fn new(id: EndpointId, owner: ProcessId) -> Self {
+        Self {
+            id,
+            owner,
+            pending_messages: VecDeque::new(),
+            blocked_senders: WaitQueue::default(),
+            waiting_receivers: WaitQueue::default(),
+            metrics: EndpointMetrics::default(),
+            broadcast: None,
+        }
+    }
+
+    /// Whether this is a broadcast endpoint
+    pub fn is_broadcast(&self) -> bool {
+        self.broadcast.is_some()
+    }
+
+    /// Queued messages published by `broadcast`
+    pub fn backlog_from(&self, broadcast: EndpointId) -> usize {
+        self.pending_messages
+            .iter()
+            .filter(|m| m.broadcast == Some(broadcast))
+            .count()
+    }
+}
+
+/// Why a broadcast operation failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BroadcastError {
+    /// A capability is missing or lacks a permission
+    Capability(KernelError),
+    /// Publishing or subscribing to an ordinary endpoint, or subscribing a
+    /// broadcast endpoint
+    WrongKind,
+    /// A subscriber or per-process limit was reached
+    LimitExceeded,
+    /// Larger than [`MAX_MESSAGE_SIZE`]
+    TooLarge,
+}
+
+/// An endpoint receiving the messages published to a broadcast endpoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Subscriber {
+    /// Subscribing process
+    pub pid: ProcessId,
+    /// Its endpoint the messages are queued on
+    pub endpoint: EndpointId,
+    /// Messages it missed by being too far behind
+    pub dropped: u64,
+}
+
+/// The subscribers of a broadcast endpoint, one per process.
+#[derive(Clone, Debug, Default)]
+pub struct Subscribers {
+    subscribers: Vec<Subscriber>,
+}
+
+impl Subscribers {
+    /// Subscribe `pid`'s `endpoint`, replacing its earlier subscription.
+    /// Fails if there are already `MAX_BROADCAST_SUBSCRIBERS` others.
+    pub fn subscribe(
+        &mut self,
+        pid: ProcessId,
+        endpoint: EndpointId,
+    ) -> Result<(), BroadcastError> {
+        if let Some(subscriber) = self.subscribers.iter_mut().find(|s| s.pid == pid) {
+            subscriber.endpoint = endpoint;
+            return Ok(());
+        }
+        if self.subscribers.len() >= MAX_BROADCAST_SUBSCRIBERS {
+            return Err(BroadcastError::LimitExceeded);
+        }
+        self.subscribers.push(Subscriber {
+            pid,
+            endpoint,
+            dropped: 0,
+        });
+        Ok(())
+    }
+
+    /// Cancel `pid`'s subscription. Returns whether it had one.
+    pub fn unsubscribe(&mut self, pid: ProcessId) -> bool {
+        let before = self.subscribers.len();
+        self.subscribers.retain(|s| s.pid != pid);
+        self.subscribers.len() != before
+    }
+
+    /// Drop subscriptions whose endpoint no longer exists.
+    pub fn prune(&mut self, exists: impl Fn(EndpointId) -> bool) {
+        self.subscribers.retain(|s| exists(s.endpoint));
+    }
+
+    /// Subscribers, in subscription order.
+    pub fn iter(&self) -> impl Iterator<Item = &Subscriber> {
+        self.subscribers.iter()
+    }
+
+    /// Subscribers, to count what they missed.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Subscriber> {
+        self.subscribers.iter_mut()
+    }
+
+    /// Number of subscribers.
+    pub fn len(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// Whether nobody is subscribed.
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+}
+
+/// A process waiting for its turn on an endpoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Waiter {
+    /// Waiting process
+    pub pid: ProcessId,
+    /// Uptime (nanos) of its latest attempt
+    pub last_attempt_ns: u64,
+}
+
+/// FIFO of processes contending for one side of an endpoint.
+///
+/// A process joins at the tail the first time it has to wait and keeps its
+/// position while it retries. When `n` messages (or free slots) are
+/// available, only the first `n` waiters may take them; anyone else,
+/// including a process that was not waiting, goes behind them. Waiters are
+/// therefore served strictly in arrival order and none can be overtaken
+/// indefinitely.
+#[derive(Clone, Debug, Default)]
+pub struct WaitQueue {
+    waiters: VecDeque<Waiter>,
+}
+
+impl WaitQueue {
+    /// Position `pid` would be served at: its place in line, or the tail.
+    pub fn position(&self, pid: ProcessId) -> usize {
+        self.waiters
+            .iter()
+            .position(|w| w.pid == pid)
+            .unwrap_or(self.waiters.len())
+    }
+
+    /// Join the tail, or record another attempt if already waiting.
+    pub fn wait(&mut self, pid: ProcessId, now: u64) {
+        match self.waiters.iter_mut().find(|w| w.pid == pid) {
+            Some(waiter) => waiter.last_attempt_ns = now,
+            None => self.waiters.push_back(Waiter {
+                pid,
+                last_attempt_ns: now,
+            }),
+        }
+    }
+
+    /// Leave the line (served, or gone).
+    pub fn leave(&mut self, pid: ProcessId) {
+        self.waiters.retain(|w| w.pid != pid);
+    }
+
+    /// Drop waiters that no longer exist or stopped retrying.
+    pub fn prune(&mut self, now: u64, alive: impl Fn(ProcessId) -> bool) {
+        self.waiters
+            .retain(|w| alive(w.pid) && now.saturating_sub(w.last_attempt_ns) < WAITER_TIMEOUT_NS);
+    }
+
+    /// Waiting processes, first in line first.
+    pub fn iter(&self) -> impl Iterator<Item = &Waiter> {
+        self.waiters.iter()
+    }
+
+    /// Number of waiters.
+    pub fn len(&self) -> usize {
+        self.waiters.len()
+    }
+
+    /// Whether nobody is waiting.
+    pub fn is_empty(&self) -> bool {
+        self.waiters.is_empty()
+    }
 }
 
 /// Detailed info about an endpoint
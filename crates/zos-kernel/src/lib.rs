@@ -24,21 +24,36 @@
 //! - `system` - System struct combining Axiom and KernelCore (primary entry point)
 //! - `types` - Core kernel types (ProcessId, EndpointId, etc.)
 //! - `capability` - Capability tokens and permission checking
-//! - `ipc` - Inter-process communication types
+//! - `ipc` - Inter-process communication types (including broadcast endpoints)
 //! - `syscall` - Syscall definitions and results
 //! - `error` - Kernel error types
 //! - `core` - KernelCore implementation
 //! - `replay` - Deterministic replay support
+//! - `chaos` - Seeded fault injection for resilience testing
+//! - `timer` - Timer wheel behind process timers
+//! - `call` - Synchronous calls (call/reply)
+//! - `shm` - Shared memory regions for bulk transfers
+//! - `notification` - Notifications (signal bits without data)
+//! - `graph` - Service graph snapshots for system-map tools
+//! - `invariants` - Runtime invariant checks (`debug-invariants` feature)
 
 #![no_std]
 extern crate alloc;
 
 // Submodules
+pub mod call;
 pub mod capability;
+pub mod chaos;
 pub mod error;
+pub mod graph;
+#[cfg(feature = "debug-invariants")]
+pub mod invariants;
 pub mod ipc;
+pub mod notification;
+pub mod shm;
 pub mod syscall;
 pub mod system;
+pub mod timer;
 pub mod types;
 
 // Internal modules (now public for System)
@@ -46,22 +61,39 @@ pub mod core;
 mod replay;
 
 // Re-export all public types
+pub use call::{Call, CallError, CallId, CallTable};
 pub use capability::{axiom_check, AxiomError, Capability, CapabilitySpace, Permissions};
+pub use chaos::{ChaosConfig, FaultInjector, StorageFault};
 pub use error::KernelError;
+pub use graph::{ServiceEdge, ServiceGraph, ServiceNode};
+#[cfg(feature = "debug-invariants")]
+pub use invariants::{Invariant, InvariantViolation};
 pub use ipc::{
-    Endpoint, EndpointDetail, EndpointInfo, Message, MessageSummary, TransferredCap,
-    MAX_CAPS_PER_MESSAGE, MAX_MESSAGE_SIZE,
+    BroadcastError, Endpoint, EndpointDetail, EndpointInfo, Message, MessageSummary, Subscriber,
+    Subscribers, TransferredCap, WaitQueue, Waiter, MAX_BROADCASTS_PER_PROCESS,
+    MAX_BROADCAST_BACKLOG, MAX_BROADCAST_SUBSCRIBERS, MAX_CAPS_PER_MESSAGE, MAX_MESSAGE_SIZE,
+    MAX_QUEUED_MESSAGES, WAITER_TIMEOUT_NS,
+};
+pub use notification::{Notification, MAX_NOTIFICATIONS_PER_PROCESS};
+pub use shm::{
+    ShmAccess, ShmError, ShmId, ShmMapping, ShmRegion, ShmTable, MAX_SHM_MAPPINGS_PER_PROCESS,
+    MAX_SHM_REGIONS_PER_PROCESS, MAX_SHM_SIZE,
 };
 pub use syscall::{
     CapInfo, RevokeNotification, Syscall, SyscallResult, MSG_CAP_REVOKED, MSG_CONSOLE_INPUT,
-    SYS_CALL, SYS_CAP_DELETE, SYS_CAP_DERIVE, SYS_CAP_GRANT, SYS_CAP_INSPECT, SYS_CAP_LIST,
-    SYS_CAP_REVOKE, SYS_CONSOLE_WRITE, SYS_CREATE_ENDPOINT, SYS_DEBUG, SYS_DELETE_ENDPOINT,
-    SYS_EXIT, SYS_KILL, SYS_PS, SYS_RECV, SYS_REPLY, SYS_SEND, SYS_SEND_CAP, SYS_TIME,
-    SYS_WALLCLOCK, SYS_YIELD,
+    SYS_BROADCAST_CREATE, SYS_CALL, SYS_CALL_WAIT, SYS_CAP_DELETE, SYS_CAP_DERIVE, SYS_CAP_GRANT,
+    SYS_CAP_INSPECT, SYS_CAP_LIST, SYS_CAP_REVOKE, SYS_CONSOLE_WRITE, SYS_CREATE_ENDPOINT,
+    SYS_DEBUG, SYS_DELETE_ENDPOINT, SYS_EXIT, SYS_KILL, SYS_NOTIFY_CREATE, SYS_PROC_READ_MEM,
+    SYS_PROC_STATE, SYS_PS, SYS_PUBLISH, SYS_RECV, SYS_REPLY, SYS_SEND, SYS_SEND_CAP, SYS_SETPGID,
+    SYS_SET_MEMORY_LIMIT, SYS_SET_PRIORITY, SYS_SHM_CREATE, SYS_SHM_FETCH, SYS_SHM_FLUSH,
+    SYS_SHM_MAP, SYS_SHM_UNMAP, SYS_SIGNAL, SYS_SUBSCRIBE, SYS_TIME, SYS_TIMER_CANCEL,
+    SYS_TIMER_CREATE, SYS_WAIT, SYS_WALLCLOCK, SYS_YIELD,
 };
+pub use timer::{Timer, TimerId, MAX_TIMERS_PER_PROCESS};
 pub use types::{
-    CapSlot, EndpointId, EndpointMetrics, ObjectType, Process, ProcessId, ProcessMetrics,
-    ProcessState, SystemMetrics,
+    CapSlot, EndpointId, EndpointMetrics, NotificationId, ObjectType, PoolStats, Priority, Process,
+    ProcessId, ProcessMetrics, ProcessState, SystemMetrics, DEFAULT_MEMORY_LIMIT,
+    FAULT_OUT_OF_MEMORY, KILLED_EXIT_CODE,
 };
 
 // Re-export HAL types
@@ -75,5 +107,6 @@ pub use zos_axiom::{
 };
 
 // Re-export main types from modules
+pub use core::names::{CapRef, NameBinding, NameTable};
 pub use core::KernelCore;
-pub use system::System;
+pub use system::{encode_completion, CompletionMessage, System, STUCK_OP_ERROR};
@@ -0,0 +1,73 @@
+//! Notifications
+//!
+//! A notification is a word of signal bits (`NOTIFY_BITS`: bit 31 is
+//! reserved), for wakeups that carry no data. `SYS_NOTIFY_CREATE` makes one and gives its creator a
+//! Notification capability for it, which is granted or sent like any
+//! other. Holders with write permission OR bits into it with `SYS_SIGNAL`;
+//! holders with read permission take the pending bits with `SYS_WAIT`,
+//! which clears them. Signalling the same bit twice before a wait is seen
+//! once, so a bit means "something happened", not how often.
+//!
+//! Unlike a message, a signal allocates nothing and never fills a queue.
+//! Creating and destroying a notification are commits, as is each signal
+//! (for the audit trail); the pending bits are volatile like message
+//! queues and are not replayed. A notification is destroyed when its
+//! owner exits.
+
+use crate::types::{NotificationId, ProcessId};
+
+/// Maximum notifications owned by one process (DoS protection).
+pub const MAX_NOTIFICATIONS_PER_PROCESS: usize = 32;
+
+/// A notification object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Notification {
+    pub id: NotificationId,
+    /// Owning process
+    pub owner: ProcessId,
+    /// Bits signalled since the last wait
+    pub pending: u32,
+    /// Signals received since creation
+    pub signal_count: u64,
+}
+
+impl Notification {
+    /// A notification with nothing pending
+    pub fn new(id: NotificationId, owner: ProcessId) -> Self {
+        Self {
+            id,
+            owner,
+            pending: 0,
+            signal_count: 0,
+        }
+    }
+
+    /// OR `bits` into the pending bits.
+    pub fn signal(&mut self, bits: u32) {
+        self.pending |= bits;
+        self.signal_count += 1;
+    }
+
+    /// Take the pending bits, leaving none.
+    pub fn take(&mut self) -> u32 {
+        core::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signals_accumulate_until_taken() {
+        let mut n = Notification::new(NotificationId(1), ProcessId(2));
+        assert_eq!(n.take(), 0);
+
+        n.signal(0b0001);
+        n.signal(0b0100);
+        n.signal(0b0001);
+        assert_eq!(n.take(), 0b0101);
+        assert_eq!(n.take(), 0);
+        assert_eq!(n.signal_count, 3);
+    }
+}
@@ -3,16 +3,17 @@
 //! This module implements the `Replayable` trait, allowing system state to be
 //! reconstructed from a commit log for auditing and verification purposes.
 
-use alloc::collections::VecDeque;
 use alloc::string::String;
 
 use crate::ipc::Endpoint;
+use crate::notification::Notification;
 use crate::system::System;
 use crate::types::{
-    EndpointId, EndpointMetrics, ObjectType, Process, ProcessId, ProcessMetrics, ProcessState,
+    EndpointId, NotificationId, ObjectType, Priority, Process, ProcessId, ProcessMetrics,
+    ProcessState, DEFAULT_MEMORY_LIMIT, KILLED_EXIT_CODE,
 };
 use crate::{Capability, CapabilitySpace, Permissions};
-use zos_axiom::{ReplayError, ReplayResult, Replayable, StateHasher};
+use zos_axiom::{ObjectKey, ReplayError, ReplayResult, Replayable};
 use zos_hal::HAL;
 
 impl<H: HAL> Replayable for System<H> {
@@ -25,6 +26,9 @@ impl<H: HAL> Replayable for System<H> {
             pid: ProcessId(pid),
             name,
             state: ProcessState::Running,
+            priority: Priority::initial(ProcessId(pid)),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            pgid: ProcessId(pid),
             metrics: ProcessMetrics::default(),
         };
         self.kernel.processes.insert(ProcessId(pid), process);
@@ -37,16 +41,28 @@ impl<H: HAL> Replayable for System<H> {
             self.kernel.next_pid = pid + 1;
         }
 
+        self.refresh_object(ObjectKey::Process(pid));
         Ok(())
     }
 
-    fn replay_exit_process(&mut self, pid: u64, _code: i32) -> ReplayResult<()> {
+    fn replay_exit_process(&mut self, pid: u64, code: i32) -> ReplayResult<()> {
+        if code == KILLED_EXIT_CODE {
+            // A kill removes the process and its CSpace (endpoints follow as
+            // separate EndpointDestroyed commits). An exited process that is
+            // then killed was already reaped if it exited with the same code.
+            self.kernel.processes.remove(&ProcessId(pid));
+            self.kernel.cap_spaces.remove(&ProcessId(pid));
+            self.refresh_object(ObjectKey::Process(pid));
+            return Ok(());
+        }
+
         let process = self
             .kernel
             .processes
             .get_mut(&ProcessId(pid))
             .ok_or(ReplayError::ProcessNotFound(pid))?;
         process.state = ProcessState::Zombie;
+        self.refresh_object(ObjectKey::Process(pid));
         Ok(())
     }
 
@@ -62,6 +78,43 @@ impl<H: HAL> Replayable for System<H> {
             .get_mut(&ProcessId(pid))
             .ok_or(ReplayError::ProcessNotFound(pid))?;
         process.state = ProcessState::Zombie;
+        self.refresh_object(ObjectKey::Process(pid));
+        Ok(())
+    }
+
+    fn replay_set_priority(&mut self, pid: u64, priority: u8) -> ReplayResult<()> {
+        let priority = Priority::from_u8(priority).ok_or_else(|| {
+            ReplayError::InvalidCommit(alloc::format!("unknown priority class {}", priority))
+        })?;
+        let process = self
+            .kernel
+            .processes
+            .get_mut(&ProcessId(pid))
+            .ok_or(ReplayError::ProcessNotFound(pid))?;
+        process.priority = priority;
+        self.refresh_object(ObjectKey::Process(pid));
+        Ok(())
+    }
+
+    fn replay_set_memory_limit(&mut self, pid: u64, limit: u64) -> ReplayResult<()> {
+        let process = self
+            .kernel
+            .processes
+            .get_mut(&ProcessId(pid))
+            .ok_or(ReplayError::ProcessNotFound(pid))?;
+        process.memory_limit = limit as usize;
+        self.refresh_object(ObjectKey::Process(pid));
+        Ok(())
+    }
+
+    fn replay_set_process_group(&mut self, pid: u64, pgid: u64) -> ReplayResult<()> {
+        let process = self
+            .kernel
+            .processes
+            .get_mut(&ProcessId(pid))
+            .ok_or(ReplayError::ProcessNotFound(pid))?;
+        process.pgid = ProcessId(pgid);
+        self.refresh_object(ObjectKey::Process(pid));
         Ok(())
     }
 
@@ -103,6 +156,7 @@ impl<H: HAL> Replayable for System<H> {
             self.kernel.next_cap_id = cap_id + 1;
         }
 
+        self.refresh_object(ObjectKey::Capability { pid, slot });
         Ok(())
     }
 
@@ -113,6 +167,7 @@ impl<H: HAL> Replayable for System<H> {
             .get_mut(&ProcessId(pid))
             .ok_or(ReplayError::ProcessNotFound(pid))?;
         cspace.slots.remove(&slot);
+        self.refresh_object(ObjectKey::Capability { pid, slot });
         Ok(())
     }
 
@@ -137,12 +192,7 @@ impl<H: HAL> Replayable for System<H> {
             return Err(ReplayError::ProcessNotFound(owner));
         }
 
-        let endpoint = Endpoint {
-            id: EndpointId(id),
-            owner: ProcessId(owner),
-            pending_messages: VecDeque::new(),
-            metrics: EndpointMetrics::default(),
-        };
+        let endpoint = Endpoint::new(EndpointId(id), ProcessId(owner));
         self.kernel.endpoints.insert(EndpointId(id), endpoint);
 
         // Update next_endpoint_id to avoid collisions
@@ -150,11 +200,13 @@ impl<H: HAL> Replayable for System<H> {
             self.kernel.next_endpoint_id = id + 1;
         }
 
+        self.refresh_object(ObjectKey::Endpoint(id));
         Ok(())
     }
 
     fn replay_destroy_endpoint(&mut self, id: u64) -> ReplayResult<()> {
         self.kernel.endpoints.remove(&EndpointId(id));
+        self.refresh_object(ObjectKey::Endpoint(id));
         Ok(())
     }
 
@@ -169,41 +221,43 @@ impl<H: HAL> Replayable for System<H> {
         Ok(())
     }
 
-    fn state_hash(&self) -> [u8; 32] {
-        let mut hasher = StateHasher::new();
-
-        // Hash process table
-        hasher.write_u64(self.kernel.processes.len() as u64);
-        for (pid, proc) in &self.kernel.processes {
-            hasher.write_u64(pid.0);
-            hasher.write_str(&proc.name);
-            hasher.write_u8(process_state_to_u8(proc.state));
+    fn replay_create_notification(&mut self, id: u64, owner: u64) -> ReplayResult<()> {
+        if !self.kernel.processes.contains_key(&ProcessId(owner)) {
+            return Err(ReplayError::ProcessNotFound(owner));
         }
 
-        // Hash capability spaces
-        hasher.write_u64(self.kernel.cap_spaces.len() as u64);
-        for (pid, cspace) in &self.kernel.cap_spaces {
-            hasher.write_u64(pid.0);
-            hasher.write_u64(cspace.slots.len() as u64);
-            for (slot, cap) in &cspace.slots {
-                hasher.write_u32(*slot);
-                hasher.write_u64(cap.id);
-                hasher.write_u8(cap.object_type as u8);
-                hasher.write_u64(cap.object_id);
-                hasher.write_u8(cap.permissions.to_byte());
-                hasher.write_u32(cap.generation);
-                hasher.write_u64(cap.expires_at);
-            }
-        }
+        let notification = Notification::new(NotificationId(id), ProcessId(owner));
+        self.kernel
+            .notifications
+            .insert(NotificationId(id), notification);
 
-        // Hash endpoints
-        hasher.write_u64(self.kernel.endpoints.len() as u64);
-        for (id, ep) in &self.kernel.endpoints {
-            hasher.write_u64(id.0);
-            hasher.write_u64(ep.owner.0);
+        // Update next_notification_id to avoid collisions
+        if id >= self.kernel.next_notification_id {
+            self.kernel.next_notification_id = id + 1;
         }
 
-        hasher.finalize()
+        self.refresh_object(ObjectKey::Notification(id));
+        Ok(())
+    }
+
+    fn replay_destroy_notification(&mut self, id: u64) -> ReplayResult<()> {
+        self.kernel.notifications.remove(&NotificationId(id));
+        self.refresh_object(ObjectKey::Notification(id));
+        Ok(())
+    }
+
+    fn replay_notification_signaled(
+        &mut self,
+        _from_pid: u64,
+        _id: u64,
+        _bits: u32,
+    ) -> ReplayResult<()> {
+        // Pending bits are volatile like message queues and not replayed
+        Ok(())
+    }
+
+    fn state_hash(&self) -> [u8; 32] {
+        self.state_digest().finalize()
     }
 }
 
@@ -216,19 +270,12 @@ fn map_object_type(object_type: u8) -> ReplayResult<ObjectType> {
         4 => Ok(ObjectType::Irq),
         5 => Ok(ObjectType::IoPort),
         6 => Ok(ObjectType::Console),
+        12 => Ok(ObjectType::Notification),
+        13 => Ok(ObjectType::Debugger),
         _ => Err(ReplayError::UnknownObjectType(object_type)),
     }
 }
 
-/// Convert ProcessState to u8 for hashing
-fn process_state_to_u8(state: ProcessState) -> u8 {
-    match state {
-        ProcessState::Running => 0,
-        ProcessState::Blocked => 1,
-        ProcessState::Zombie => 2,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,6 +352,18 @@ mod tests {
         assert_eq!(proc.state, ProcessState::Zombie);
     }
 
+    #[test]
+    fn test_replay_killed_process_is_removed() {
+        let mut system: System<TestHal> = System::new_for_replay();
+
+        system.replay_create_process(1, 0, String::from("test")).unwrap();
+        system.replay_exit_process(1, 0).unwrap();
+        system.replay_exit_process(1, KILLED_EXIT_CODE).unwrap();
+
+        assert!(!system.kernel.processes.contains_key(&ProcessId(1)));
+        assert!(!system.kernel.cap_spaces.contains_key(&ProcessId(1)));
+    }
+
     #[test]
     fn test_replay_exit_process_not_found() {
         let mut system: System<TestHal> = System::new_for_replay();
@@ -532,6 +591,33 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    // ========================================================================
+    // notification tests
+    // ========================================================================
+
+    #[test]
+    fn test_replay_notification_lifecycle() {
+        let mut system: System<TestHal> = System::new_for_replay();
+
+        system.replay_create_process(1, 0, String::from("test")).unwrap();
+        system.replay_create_notification(7, 1).unwrap();
+        assert_eq!(system.kernel.next_notification_id, 8);
+
+        // Signals are audit only: nothing is left pending after replay
+        system.replay_notification_signaled(1, 7, 0b11).unwrap();
+        let notification = system.kernel.notifications.get(&NotificationId(7)).unwrap();
+        assert_eq!(notification.owner, ProcessId(1));
+        assert_eq!(notification.pending, 0);
+
+        system.replay_destroy_notification(7).unwrap();
+        assert!(system.kernel.notifications.is_empty());
+
+        assert!(matches!(
+            system.replay_create_notification(8, 999),
+            Err(ReplayError::ProcessNotFound(999))
+        ));
+    }
+
     // ========================================================================
     // state_hash tests
     // ========================================================================
@@ -638,6 +724,29 @@ mod tests {
         assert_ne!(hash1, hash2, "Endpoint changes should affect hash");
     }
 
+    #[test]
+    fn test_state_digest_tracks_replay() {
+        let mut system: System<TestHal> = System::new_for_replay();
+
+        system.replay_create_process(1, 0, String::from("a")).unwrap();
+        system.replay_create_process(2, 0, String::from("b")).unwrap();
+        system.replay_create_endpoint(1, 1).unwrap();
+        system.replay_insert_capability(1, 0, 100, 1, 1, 0x07).unwrap();
+        system.replay_insert_capability(2, 0, 101, 1, 1, 0x02).unwrap();
+        system.replay_insert_capability(2, 1, 102, 1, 1, 0x01).unwrap();
+        assert!(system.verify_state_digest());
+
+        system.replay_remove_capability(2, 1).unwrap();
+        system.replay_exit_process(1, 0).unwrap();
+        assert!(system.verify_state_digest());
+
+        // A kill drops the CSpace along with the process
+        system.replay_exit_process(2, KILLED_EXIT_CODE).unwrap();
+        system.replay_destroy_endpoint(1).unwrap();
+        assert!(system.verify_state_digest());
+        assert_eq!(system.state_digest().len(), 2);
+    }
+
     // ========================================================================
     // map_object_type tests
     // ========================================================================
@@ -650,6 +759,8 @@ mod tests {
         assert_eq!(map_object_type(4).unwrap(), ObjectType::Irq);
         assert_eq!(map_object_type(5).unwrap(), ObjectType::IoPort);
         assert_eq!(map_object_type(6).unwrap(), ObjectType::Console);
+        assert_eq!(map_object_type(12).unwrap(), ObjectType::Notification);
+        assert_eq!(map_object_type(13).unwrap(), ObjectType::Debugger);
     }
 
     #[test]
@@ -0,0 +1,347 @@
+//! Shared memory regions
+//!
+//! `SYS_SHM_CREATE` makes a zeroed region and hands its creator a Memory
+//! capability for it. The capability is granted or sent like any other;
+//! whoever holds it can map the region with `SYS_SHM_MAP`, at an address
+//! in their own memory, writable if the capability allows writing.
+//!
+//! A mapping is a window onto the start of the region. `SYS_SHM_FLUSH`
+//! copies bytes of the window into the region and `SYS_SHM_FETCH` copies
+//! them back out, so bulk data moves between processes without going
+//! through a message. The kernel only checks and records these accesses;
+//! the region's bytes live with the HAL (a `SharedArrayBuffer` in the
+//! browser), which does the copying once the kernel has allowed it.
+//!
+//! A mapping lasts until it is unmapped or its process exits, even after
+//! the capability has been sent on. A region lasts while a mapping or a
+//! capability refers to it; once neither does, it is reaped on the next
+//! unmap, process exit or region creation.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use crate::error::KernelError;
+use crate::types::ProcessId;
+
+/// Largest region (bytes).
+pub const MAX_SHM_SIZE: u32 = 1 << 20;
+
+/// Maximum live regions created by one process (DoS protection).
+pub const MAX_SHM_REGIONS_PER_PROCESS: usize = 16;
+
+/// Maximum mappings per process.
+pub const MAX_SHM_MAPPINGS_PER_PROCESS: usize = 16;
+
+/// Region identifier, unique since boot (the Memory capability's object ID).
+pub type ShmId = u64;
+
+/// A shared memory region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShmRegion {
+    pub id: ShmId,
+    /// Size in bytes
+    pub size: u32,
+    /// Process that created it
+    pub creator: ProcessId,
+}
+
+/// A region mapped into a process's memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShmMapping {
+    pub region: ShmId,
+    /// Address of the window in the process's memory
+    pub addr: u32,
+    /// Window length: the first `len` bytes of the region
+    pub len: u32,
+    /// Whether the process may flush into the region
+    pub writable: bool,
+}
+
+/// A checked copy between a mapping and its region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShmAccess {
+    pub region: ShmId,
+    /// Address of the bytes in the process's memory
+    pub addr: u32,
+    /// Offset of the bytes in the region
+    pub offset: u32,
+    pub len: u32,
+}
+
+/// Why a shared memory operation failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShmError {
+    /// The Memory capability is missing or lacks a permission
+    Capability(KernelError),
+    /// Zero or larger than [`MAX_SHM_SIZE`]
+    InvalidSize,
+    /// A per-process limit was reached
+    LimitExceeded,
+    /// No such region
+    NoRegion,
+    /// No mapping at that address
+    NotMapped,
+    /// The window overlaps another mapping of the process
+    Overlap,
+    /// The bytes fall outside the mapping
+    OutOfRange,
+    /// Flush into a read-only mapping
+    ReadOnly,
+}
+
+/// Every region and mapping.
+#[derive(Debug)]
+pub struct ShmTable {
+    regions: BTreeMap<ShmId, ShmRegion>,
+    /// Mappings by process and address
+    mappings: BTreeMap<(ProcessId, u32), ShmMapping>,
+    next_id: ShmId,
+}
+
+impl Default for ShmTable {
+    fn default() -> Self {
+        Self {
+            regions: BTreeMap::new(),
+            mappings: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl ShmTable {
+    /// Create a region of `size` bytes for `creator`.
+    pub fn create(&mut self, creator: ProcessId, size: u32) -> Result<ShmId, ShmError> {
+        if size == 0 || size > MAX_SHM_SIZE {
+            return Err(ShmError::InvalidSize);
+        }
+        let created = self
+            .regions
+            .values()
+            .filter(|r| r.creator == creator)
+            .count();
+        if created >= MAX_SHM_REGIONS_PER_PROCESS {
+            return Err(ShmError::LimitExceeded);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.regions.insert(id, ShmRegion { id, size, creator });
+        Ok(id)
+    }
+
+    /// A region by ID.
+    pub fn region(&self, id: ShmId) -> Option<&ShmRegion> {
+        self.regions.get(&id)
+    }
+
+    /// Map up to `len` bytes of `region` at `addr` in `pid`'s memory.
+    /// Returns the mapped length, capped at the region size.
+    pub fn map(
+        &mut self,
+        pid: ProcessId,
+        region: ShmId,
+        addr: u32,
+        len: u32,
+        writable: bool,
+    ) -> Result<u32, ShmError> {
+        let size = self.regions.get(&region).ok_or(ShmError::NoRegion)?.size;
+        let len = len.min(size);
+        if len == 0 || addr.checked_add(len).is_none() {
+            return Err(ShmError::OutOfRange);
+        }
+        if self.mappings_of(pid).count() >= MAX_SHM_MAPPINGS_PER_PROCESS {
+            return Err(ShmError::LimitExceeded);
+        }
+        let end = addr + len;
+        if self
+            .mappings_of(pid)
+            .any(|m| addr < m.addr + m.len && m.addr < end)
+        {
+            return Err(ShmError::Overlap);
+        }
+        self.mappings.insert(
+            (pid, addr),
+            ShmMapping {
+                region,
+                addr,
+                len,
+                writable,
+            },
+        );
+        Ok(len)
+    }
+
+    /// Remove `pid`'s mapping at `addr`.
+    pub fn unmap(&mut self, pid: ProcessId, addr: u32) -> Option<ShmMapping> {
+        self.mappings.remove(&(pid, addr))
+    }
+
+    /// Remove every mapping of `pid`.
+    pub fn unmap_all(&mut self, pid: ProcessId) {
+        self.mappings.retain(|&(owner, _), _| owner != pid);
+    }
+
+    /// `pid`'s mapping at `addr`.
+    pub fn mapping(&self, pid: ProcessId, addr: u32) -> Option<&ShmMapping> {
+        self.mappings.get(&(pid, addr))
+    }
+
+    /// Check a copy of `len` bytes at `offset` between `pid`'s mapping at
+    /// `addr` and its region: into the region if `flush`, out of it
+    /// otherwise.
+    pub fn access(
+        &self,
+        pid: ProcessId,
+        addr: u32,
+        offset: u32,
+        len: u32,
+        flush: bool,
+    ) -> Result<ShmAccess, ShmError> {
+        let mapping = self.mapping(pid, addr).ok_or(ShmError::NotMapped)?;
+        if offset.checked_add(len).is_none_or(|end| end > mapping.len) {
+            return Err(ShmError::OutOfRange);
+        }
+        if flush && !mapping.writable {
+            return Err(ShmError::ReadOnly);
+        }
+        Ok(ShmAccess {
+            region: mapping.region,
+            addr: addr + offset,
+            offset,
+            len,
+        })
+    }
+
+    /// Reap every region neither mapped nor in `referenced` (the regions
+    /// some capability still refers to). Returns the reaped regions.
+    pub fn reap(&mut self, referenced: &BTreeSet<ShmId>) -> Vec<ShmId> {
+        let mapped: BTreeSet<ShmId> = self.mappings.values().map(|m| m.region).collect();
+        let reaped: Vec<ShmId> = self
+            .regions
+            .keys()
+            .copied()
+            .filter(|id| !mapped.contains(id) && !referenced.contains(id))
+            .collect();
+        for id in &reaped {
+            self.regions.remove(id);
+        }
+        reaped
+    }
+
+    /// Every live region.
+    pub fn regions(&self) -> impl Iterator<Item = &ShmRegion> {
+        self.regions.values()
+    }
+
+    /// Number of live regions.
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Whether there are no live regions.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    fn mappings_of(&self, pid: ProcessId) -> impl Iterator<Item = &ShmMapping> {
+        self.mappings
+            .range((pid, 0)..=(pid, u32::MAX))
+            .map(|(_, m)| m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const A: ProcessId = ProcessId(5);
+    const B: ProcessId = ProcessId(6);
+
+    #[test]
+    fn test_create_limits() {
+        let mut shm = ShmTable::default();
+        assert_eq!(shm.create(A, 0), Err(ShmError::InvalidSize));
+        assert_eq!(shm.create(A, MAX_SHM_SIZE + 1), Err(ShmError::InvalidSize));
+
+        for _ in 0..MAX_SHM_REGIONS_PER_PROCESS {
+            shm.create(A, 4096).unwrap();
+        }
+        assert_eq!(shm.create(A, 4096), Err(ShmError::LimitExceeded));
+        assert!(shm.create(B, MAX_SHM_SIZE).is_ok());
+    }
+
+    #[test]
+    fn test_map_caps_length_and_rejects_overlap() {
+        let mut shm = ShmTable::default();
+        let id = shm.create(A, 4096).unwrap();
+
+        assert_eq!(shm.map(A, id, 0x1000, 8192, true), Ok(4096));
+        assert_eq!(shm.map(A, id, 0x1800, 16, true), Err(ShmError::Overlap));
+        assert_eq!(shm.map(A, id, 0x0800, 0x801, true), Err(ShmError::Overlap));
+        assert_eq!(shm.map(A, id, 0x2000, 16, false), Ok(16));
+        assert_eq!(
+            shm.map(A, id, u32::MAX - 8, 16, false),
+            Err(ShmError::OutOfRange)
+        );
+        assert_eq!(
+            shm.map(A, id + 1, 0x8000, 16, false),
+            Err(ShmError::NoRegion)
+        );
+
+        // Mappings of different processes never overlap
+        assert_eq!(shm.map(B, id, 0x1000, 16, false), Ok(16));
+    }
+
+    #[test]
+    fn test_access_checks_range_and_permission() {
+        let mut shm = ShmTable::default();
+        let id = shm.create(A, 4096).unwrap();
+        shm.map(A, id, 0x1000, 256, true).unwrap();
+        shm.map(B, id, 0x4000, 4096, false).unwrap();
+
+        let access = shm.access(A, 0x1000, 16, 240, true).unwrap();
+        assert_eq!(
+            access,
+            ShmAccess {
+                region: id,
+                addr: 0x1010,
+                offset: 16,
+                len: 240
+            }
+        );
+        assert_eq!(
+            shm.access(A, 0x1000, 16, 241, true),
+            Err(ShmError::OutOfRange)
+        );
+        assert_eq!(
+            shm.access(A, 0x1000, u32::MAX, 2, false),
+            Err(ShmError::OutOfRange)
+        );
+        assert_eq!(shm.access(A, 0x1010, 0, 1, false), Err(ShmError::NotMapped));
+
+        assert_eq!(
+            shm.access(B, 0x4000, 0, 4096, true),
+            Err(ShmError::ReadOnly)
+        );
+        assert!(shm.access(B, 0x4000, 0, 4096, false).is_ok());
+    }
+
+    #[test]
+    fn test_reap_keeps_mapped_and_referenced_regions() {
+        let mut shm = ShmTable::default();
+        let mapped = shm.create(A, 64).unwrap();
+        let held = shm.create(A, 64).unwrap();
+        let dropped = shm.create(A, 64).unwrap();
+        shm.map(B, mapped, 0, 64, false).unwrap();
+
+        let referenced = BTreeSet::from([held]);
+        assert_eq!(shm.reap(&referenced), vec![dropped]);
+        assert_eq!(shm.len(), 2);
+
+        // The mapping outlives the capability, and the region outlives both
+        // only while one of them is left
+        shm.unmap_all(B);
+        assert_eq!(shm.reap(&BTreeSet::new()), vec![mapped, held]);
+        assert!(shm.is_empty());
+    }
+}
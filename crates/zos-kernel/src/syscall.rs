@@ -81,12 +81,24 @@ pub enum Syscall {
         data: Vec<u8>,
         cap_slots: Vec<CapSlot>,
     },
+    /// Send to a service by name, resolved on first use (SYS_SEND_NAMED 0x45)
+    SendNamed {
+        name: String,
+        tag: u32,
+        data: Vec<u8>,
+    },
     /// Call (send + wait for reply) (SYS_CALL 0x42)
     Call {
         endpoint_slot: CapSlot,
         tag: u32,
         data: Vec<u8>,
     },
+    /// Reply to a call whose request was received (SYS_REPLY 0x43)
+    Reply {
+        caller: ProcessId,
+        tag: u32,
+        data: Vec<u8>,
+    },
     /// Kill a process (SYS_KILL 0x13 - requires Process capability)
     Kill { target_pid: ProcessId },
 }
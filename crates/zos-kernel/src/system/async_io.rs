@@ -0,0 +1,362 @@
+//! Async I/O syscall handlers and completion encoding
+//!
+//! Storage (0x70-0x7A), keystore (0x80-0x84) and network (0x90) syscalls
+//! are decoded into one `AsyncOp` and submitted through
+//! `HAL::submit_async`, which returns a request ID for the process to wait
+//! on. Whatever platform runs them, results come back through
+//! `HAL::poll_async_completions`; `System::poll_async_completions` encodes
+//! each into the IPC message the requesting service expects:
+//!
+//! - Storage: `MSG_STORAGE_RESULT` carrying a `zos_ipc::storage::StorageResult`
+//! - Keystore: `MSG_KEYSTORE_RESULT`, same envelope
+//! - Network: `MSG_NET_RESULT`: [request_id: u32, result_type: u8, data_len: u32, data]
+//!
+//! Delivery (routing through Init, batching) is up to the runtime.
+//!
+//! `System::fail_stuck_async_ops` is the way out for a request the platform
+//! never answers: it completes each one outstanding past a threshold with
+//! an error, which reaches the service like any other result.
+
+use alloc::vec::Vec;
+
+use super::System;
+use crate::core::KernelCore;
+use crate::types::ProcessId;
+use zos_axiom::CommitType;
+use zos_hal::{AsyncChannel, AsyncCompletion, AsyncOp, AsyncOutcome, PendingAsyncOp, HAL};
+use zos_ipc::storage::{ResultKind, StorageResult};
+use zos_ipc::syscall as sys;
+
+/// A completion encoded for delivery to the process that asked for it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompletionMessage {
+    pub pid: ProcessId,
+    pub channel: AsyncChannel,
+    /// IPC tag the receiving service dispatches on
+    pub tag: u32,
+    pub payload: Vec<u8>,
+}
+
+impl<H: HAL> System<H> {
+    /// Collect the async operations the HAL finished since the last poll.
+    pub fn poll_async_completions(&self) -> Vec<CompletionMessage> {
+        self.hal()
+            .poll_async_completions()
+            .iter()
+            .map(encode_completion)
+            .collect()
+    }
+
+    /// Async operations still waiting for a completion, oldest first.
+    pub fn pending_async_ops(&self) -> Vec<PendingAsyncOp> {
+        let mut ops = self.hal().pending_async_ops();
+        ops.sort_by_key(|op| (op.submitted_at, op.request_id));
+        ops
+    }
+
+    /// Fail every async operation outstanding for at least `min_age_nanos`.
+    ///
+    /// Each completes with [`STUCK_OP_ERROR`], delivered with the next poll
+    /// like a real result. Returns the operations failed.
+    pub fn fail_stuck_async_ops(&self, min_age_nanos: u64) -> Vec<PendingAsyncOp> {
+        let hal = self.hal();
+        stuck_ops(self.pending_async_ops(), hal.now_nanos(), min_age_nanos)
+            .into_iter()
+            // An error means it completed after all since it was listed
+            .filter(|op| {
+                hal.fail_async(op.channel, op.request_id, STUCK_OP_ERROR)
+                    .is_ok()
+            })
+            .collect()
+    }
+}
+
+/// Error a force-failed operation completes with.
+pub const STUCK_OP_ERROR: &str = "Operation timed out";
+
+/// The operations in `ops` at least `min_age_nanos` old at `now`.
+fn stuck_ops(ops: Vec<PendingAsyncOp>, now: u64, min_age_nanos: u64) -> Vec<PendingAsyncOp> {
+    ops.into_iter()
+        .filter(|op| op.age_nanos(now) >= min_age_nanos)
+        .collect()
+}
+
+/// Encode a completion as the IPC message its service expects.
+pub fn encode_completion(completion: &AsyncCompletion) -> CompletionMessage {
+    let request_id = completion.request_id;
+    let (tag, payload) = match completion.channel {
+        AsyncChannel::Storage => (
+            zos_ipc::storage::MSG_STORAGE_RESULT,
+            encode_storage_result(request_id, &completion.outcome),
+        ),
+        AsyncChannel::Keystore => (
+            zos_ipc::keystore::MSG_KEYSTORE_RESULT,
+            encode_storage_result(request_id, &completion.outcome),
+        ),
+        AsyncChannel::Network => (
+            zos_ipc::net::MSG_NET_RESULT,
+            encode_network_result(request_id, &completion.outcome),
+        ),
+    };
+    CompletionMessage {
+        pid: ProcessId(completion.pid),
+        channel: completion.channel,
+        tag,
+        payload,
+    }
+}
+
+fn encode_storage_result(request_id: u32, outcome: &AsyncOutcome) -> Vec<u8> {
+    let (kind, data): (ResultKind, &[u8]) = match outcome {
+        AsyncOutcome::ReadOk(data) => (ResultKind::ReadOk, data),
+        AsyncOutcome::NotFound => (ResultKind::NotFound, &[]),
+        AsyncOutcome::WriteOk => (ResultKind::WriteOk, &[]),
+        AsyncOutcome::ListOk(keys_json) => (ResultKind::ListOk, keys_json),
+        AsyncOutcome::ExistsOk(exists) => {
+            return StorageResult::exists(request_id, *exists).encode()
+        }
+        AsyncOutcome::Error(message) => (ResultKind::Error, message.as_bytes()),
+        AsyncOutcome::Cancelled => (ResultKind::Cancelled, &[]),
+        // Not a storage outcome; reported as a failure rather than dropped
+        AsyncOutcome::Response(_) => (ResultKind::Error, b"Unexpected network result"),
+    };
+    StorageResult::new(request_id, kind, data).encode()
+}
+
+fn encode_network_result(request_id: u32, outcome: &AsyncOutcome) -> Vec<u8> {
+    // The result JSON carries the real status; result_type is always NET_OK
+    // except when the platform never got a response to describe
+    let (result_type, data): (u8, &[u8]) = match outcome {
+        AsyncOutcome::Response(json) => (0, json),
+        AsyncOutcome::Error(message) => (1, message.as_bytes()),
+        _ => (1, b"Unexpected storage result"),
+    };
+    let mut payload = Vec::with_capacity(9 + data.len());
+    payload.extend_from_slice(&request_id.to_le_bytes());
+    payload.push(result_type);
+    payload.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    payload.extend_from_slice(data);
+    payload
+}
+
+/// Execute a storage, keystore or network syscall.
+///
+/// Returns the request ID the completion will carry, or -1 if the request
+/// is malformed or the HAL refused it.
+pub(in crate::system) fn execute_async_syscall<H: HAL>(
+    core: &KernelCore<H>,
+    syscall_num: u32,
+    sender: ProcessId,
+    args: [u32; 4],
+    data: &[u8],
+) -> (i64, Vec<CommitType>) {
+    if syscall_num == sys::SYS_STORAGE_CANCEL {
+        return match core.hal().storage_cancel(sender.0, args[0]) {
+            Ok(()) => (0, Vec::new()),
+            Err(_) => (-1, Vec::new()),
+        };
+    }
+    if syscall_num == sys::SYS_STORAGE_BATCH_WRITE {
+        let Some(items) = decode_batch(data) else {
+            return (-1, Vec::new());
+        };
+        return submit(core, sender, AsyncOp::StorageBatchWrite { items: &items });
+    }
+    let Some(op) = decode_op(syscall_num, data) else {
+        return (-1, Vec::new());
+    };
+    submit(core, sender, op)
+}
+
+fn submit<H: HAL>(
+    core: &KernelCore<H>,
+    sender: ProcessId,
+    op: AsyncOp<'_>,
+) -> (i64, Vec<CommitType>) {
+    match core.hal().submit_async(sender.0, op) {
+        Ok(request_id) => (request_id as i64, Vec::new()),
+        Err(_) => (-1, Vec::new()),
+    }
+}
+
+/// Decode a single-key syscall into the operation it asks for.
+fn decode_op(syscall_num: u32, data: &[u8]) -> Option<AsyncOp<'_>> {
+    let text = || core::str::from_utf8(data).ok();
+    Some(match syscall_num {
+        sys::SYS_STORAGE_READ => AsyncOp::StorageRead { key: text()? },
+        sys::SYS_STORAGE_WRITE => {
+            let (key, value) = decode_key_value(data)?;
+            AsyncOp::StorageWrite { key, value }
+        }
+        sys::SYS_STORAGE_DELETE => AsyncOp::StorageDelete { key: text()? },
+        sys::SYS_STORAGE_LIST => AsyncOp::StorageList { prefix: text()? },
+        sys::SYS_STORAGE_EXISTS => AsyncOp::StorageExists { key: text()? },
+        sys::SYS_KEYSTORE_READ => AsyncOp::KeystoreRead { key: text()? },
+        sys::SYS_KEYSTORE_WRITE => {
+            let (key, value) = decode_key_value(data)?;
+            AsyncOp::KeystoreWrite { key, value }
+        }
+        sys::SYS_KEYSTORE_DELETE => AsyncOp::KeystoreDelete { key: text()? },
+        sys::SYS_KEYSTORE_LIST => AsyncOp::KeystoreList { prefix: text()? },
+        sys::SYS_KEYSTORE_EXISTS => AsyncOp::KeystoreExists { key: text()? },
+        sys::SYS_NETWORK_FETCH => AsyncOp::NetworkFetch { request: data },
+        _ => return None,
+    })
+}
+
+/// Decode a write payload: [key_len: u32, key: [u8], value: [u8]]
+fn decode_key_value(data: &[u8]) -> Option<(&str, &[u8])> {
+    let key_len = u32::from_le_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    let key = core::str::from_utf8(data.get(4..4usize.checked_add(key_len)?)?).ok()?;
+    Some((key, &data[4 + key_len..]))
+}
+
+/// Decode a batch write payload:
+/// [count: u32, (key_len: u32, key: [u8], value_len: u32, value: [u8])*]
+fn decode_batch(data: &[u8]) -> Option<Vec<(&str, &[u8])>> {
+    let mut rest = data;
+    let mut take = |len: usize| -> Option<&[u8]> {
+        let (head, tail) = (rest.get(..len)?, rest.get(len..)?);
+        rest = tail;
+        Some(head)
+    };
+    let count = u32::from_le_bytes(take(4)?.try_into().ok()?) as usize;
+    // Every item takes at least 8 bytes, which bounds the allocation
+    let mut items = Vec::with_capacity(count.min(data.len() / 8));
+    for _ in 0..count {
+        let key_len = u32::from_le_bytes(take(4)?.try_into().ok()?) as usize;
+        let key = core::str::from_utf8(take(key_len)?).ok()?;
+        let value_len = u32::from_le_bytes(take(4)?.try_into().ok()?) as usize;
+        items.push((key, take(value_len)?));
+    }
+    Some(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec;
+    use zos_ipc::storage::DecodeError;
+
+    fn completion(channel: AsyncChannel, outcome: AsyncOutcome) -> AsyncCompletion {
+        AsyncCompletion {
+            channel,
+            request_id: 7,
+            pid: 12,
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_storage_and_keystore_completions_share_the_envelope() {
+        let read = AsyncOutcome::ReadOk(b"data".to_vec());
+        let storage = encode_completion(&completion(AsyncChannel::Storage, read.clone()));
+        let keystore = encode_completion(&completion(AsyncChannel::Keystore, read));
+
+        assert_eq!(storage.pid, ProcessId(12));
+        assert_eq!(storage.tag, zos_ipc::storage::MSG_STORAGE_RESULT);
+        assert_eq!(keystore.tag, zos_ipc::keystore::MSG_KEYSTORE_RESULT);
+        assert_eq!(storage.payload, keystore.payload);
+
+        let result = StorageResult::decode(&storage.payload).unwrap();
+        assert_eq!(result.request_id, 7);
+        assert_eq!(result.kind, ResultKind::ReadOk);
+        assert_eq!(result.data, b"data");
+    }
+
+    #[test]
+    fn test_storage_outcome_kinds() {
+        let cases = [
+            (AsyncOutcome::NotFound, ResultKind::NotFound),
+            (AsyncOutcome::WriteOk, ResultKind::WriteOk),
+            (AsyncOutcome::ListOk(b"[]".to_vec()), ResultKind::ListOk),
+            (AsyncOutcome::ExistsOk(true), ResultKind::ExistsOk),
+            (AsyncOutcome::Error(String::from("disk")), ResultKind::Error),
+            (AsyncOutcome::Cancelled, ResultKind::Cancelled),
+        ];
+        for (outcome, kind) in cases {
+            let message = encode_completion(&completion(AsyncChannel::Storage, outcome));
+            let result: Result<StorageResult, DecodeError> =
+                StorageResult::decode(&message.payload);
+            assert_eq!(result.unwrap().kind, kind);
+        }
+        let exists = encode_completion(&completion(
+            AsyncChannel::Storage,
+            AsyncOutcome::ExistsOk(true),
+        ));
+        assert!(StorageResult::decode(&exists.payload)
+            .unwrap()
+            .exists_flag());
+    }
+
+    #[test]
+    fn test_network_completion_format() {
+        let json = br#"{"result":{"Ok":{}}}"#;
+        let message = encode_completion(&completion(
+            AsyncChannel::Network,
+            AsyncOutcome::Response(json.to_vec()),
+        ));
+        assert_eq!(message.tag, zos_ipc::net::MSG_NET_RESULT);
+        assert_eq!(&message.payload[0..4], &7u32.to_le_bytes());
+        assert_eq!(message.payload[4], 0);
+        assert_eq!(&message.payload[5..9], &(json.len() as u32).to_le_bytes());
+        assert_eq!(&message.payload[9..], json);
+    }
+
+    #[test]
+    fn test_stuck_ops_threshold() {
+        let op = |request_id, submitted_at| PendingAsyncOp {
+            channel: AsyncChannel::Storage,
+            request_id,
+            pid: 3,
+            submitted_at,
+        };
+        let ops = vec![op(1, 100), op(2, 900), op(3, 500)];
+        let stuck: Vec<u32> = stuck_ops(ops.clone(), 1_000, 500)
+            .iter()
+            .map(|op| op.request_id)
+            .collect();
+        assert_eq!(stuck, vec![1, 3]);
+        assert!(stuck_ops(ops, 1_000, 2_000).is_empty());
+    }
+
+    #[test]
+    fn test_decode_write_payloads() {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(b"/k1value");
+        assert!(matches!(
+            decode_op(sys::SYS_STORAGE_WRITE, &data),
+            Some(AsyncOp::StorageWrite {
+                key: "/k1",
+                value: b"value"
+            })
+        ));
+        assert!(matches!(
+            decode_op(sys::SYS_KEYSTORE_WRITE, &data),
+            Some(AsyncOp::KeystoreWrite { key: "/k1", .. })
+        ));
+        // Key length past the end of the payload
+        assert!(decode_op(sys::SYS_STORAGE_WRITE, &[9, 0, 0, 0, b'a']).is_none());
+        assert!(decode_op(sys::SYS_STORAGE_WRITE, &[1, 0]).is_none());
+        assert!(decode_op(sys::SYS_STORAGE_READ, &[0xFF]).is_none());
+    }
+
+    #[test]
+    fn test_decode_batch() {
+        let mut data = 2u32.to_le_bytes().to_vec();
+        for (key, value) in [("/a", &b"1"[..]), ("/bc", &b""[..])] {
+            data.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            data.extend_from_slice(key.as_bytes());
+            data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            data.extend_from_slice(value);
+        }
+        assert_eq!(
+            decode_batch(&data),
+            Some(vec![("/a", &b"1"[..]), ("/bc", &b""[..])])
+        );
+        assert_eq!(decode_batch(&data[..data.len() - 1]), None);
+        // A huge count with no items behind it fails without allocating for it
+        assert_eq!(decode_batch(&u32::MAX.to_le_bytes()), None);
+    }
+}
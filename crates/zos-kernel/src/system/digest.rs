@@ -0,0 +1,114 @@
+//! Incremental state digest
+//!
+//! The System keeps a `StateDigest` in step with KernelCore: every commit it
+//! records refreshes the digests of the objects that commit touched, so
+//! `state_hash()` never walks the whole state. Replay refreshes the same
+//! objects as it applies commits, which keeps live and replayed hashes
+//! comparable.
+
+use alloc::vec::Vec;
+
+use crate::types::{EndpointId, NotificationId, ProcessId, ProcessState};
+use zos_axiom::{touched_objects, CommitType, ObjectKey, StateDigest};
+use zos_hal::HAL;
+
+use super::System;
+
+impl<H: HAL> System<H> {
+    /// Refresh the digests of the objects a commit touched.
+    pub(crate) fn refresh_digest(&mut self, commit_type: &CommitType) {
+        let mut touched = Vec::new();
+        touched_objects(commit_type, &mut touched);
+        for key in touched {
+            self.refresh_object(key);
+        }
+    }
+
+    /// Recompute one object's digest from current state.
+    pub(crate) fn refresh_object(&mut self, key: ObjectKey) {
+        let digest = self.object_digest(key);
+        if let ObjectKey::Process(pid) = key {
+            // A killed process takes its whole CSpace with it
+            if !self.kernel.cap_spaces.contains_key(&ProcessId(pid)) {
+                self.digest.remove_capabilities(pid);
+            }
+        }
+        self.digest.update(key, digest);
+    }
+
+    /// Build the digest by walking all state, ignoring the maintained one.
+    ///
+    /// O(total state); used to check the incremental digest for drift.
+    pub fn rebuild_state_digest(&self) -> StateDigest {
+        let mut digest = StateDigest::new();
+        for pid in self.kernel.processes.keys() {
+            let key = ObjectKey::Process(pid.0);
+            digest.update(key, self.object_digest(key));
+        }
+        for (pid, cspace) in &self.kernel.cap_spaces {
+            for slot in cspace.slots.keys() {
+                let key = ObjectKey::Capability {
+                    pid: pid.0,
+                    slot: *slot,
+                };
+                digest.update(key, self.object_digest(key));
+            }
+        }
+        for id in self.kernel.endpoints.keys() {
+            let key = ObjectKey::Endpoint(id.0);
+            digest.update(key, self.object_digest(key));
+        }
+        for id in self.kernel.notifications.keys() {
+            let key = ObjectKey::Notification(id.0);
+            digest.update(key, self.object_digest(key));
+        }
+        digest
+    }
+
+    /// Whether the maintained digest matches a full rebuild.
+    pub fn verify_state_digest(&self) -> bool {
+        self.digest == self.rebuild_state_digest()
+    }
+
+    /// Digest of one object, or `None` if it does not exist.
+    fn object_digest(&self, key: ObjectKey) -> Option<u64> {
+        let mut hasher = key.hasher();
+        match key {
+            ObjectKey::Process(pid) => {
+                let proc = self.kernel.processes.get(&ProcessId(pid))?;
+                hasher.write_str(&proc.name);
+                hasher.write_u8(process_state_to_u8(proc.state));
+                hasher.write_u8(proc.priority.as_u8());
+                hasher.write_u64(proc.memory_limit as u64);
+                hasher.write_u64(proc.pgid.0);
+            }
+            ObjectKey::Capability { pid, slot } => {
+                let cap = self.kernel.cap_spaces.get(&ProcessId(pid))?.get(slot)?;
+                hasher.write_u64(cap.id);
+                hasher.write_u8(cap.object_type as u8);
+                hasher.write_u64(cap.object_id);
+                hasher.write_u8(cap.permissions.to_byte());
+                hasher.write_u32(cap.generation);
+                hasher.write_u64(cap.expires_at);
+            }
+            ObjectKey::Endpoint(id) => {
+                let ep = self.kernel.endpoints.get(&EndpointId(id))?;
+                hasher.write_u64(ep.owner.0);
+            }
+            ObjectKey::Notification(id) => {
+                let notification = self.kernel.notifications.get(&NotificationId(id))?;
+                hasher.write_u64(notification.owner.0);
+            }
+        }
+        Some(hasher.finish())
+    }
+}
+
+/// Convert ProcessState to u8 for hashing (and for `SYS_PS`)
+pub(super) fn process_state_to_u8(state: ProcessState) -> u8 {
+    match state {
+        ProcessState::Running => 0,
+        ProcessState::Blocked => 1,
+        ProcessState::Zombie => 2,
+    }
+}
@@ -0,0 +1,96 @@
+//! Debugger inspection syscall handlers
+//!
+//! This module contains syscall handlers for debuggers holding a Debugger
+//! capability:
+//! - `execute_proc_state()` - Snapshot another process's runtime state
+//! - `execute_proc_read_mem()` - Read another process's memory
+
+use alloc::vec::Vec;
+
+use crate::core::KernelCore;
+use crate::error::KernelError;
+use crate::types::ProcessId;
+use zos_axiom::CommitType;
+use zos_hal::{HalError, HAL};
+use zos_ipc::syscall_error;
+
+use super::digest::process_state_to_u8;
+
+/// Execute process state syscall (0x1C).
+///
+/// Returns 0 with the snapshot laid out as documented on
+/// `zos_ipc::syscall::SYS_PROC_STATE`.
+pub(in crate::system) fn execute_proc_state<H: HAL>(
+    core: &KernelCore<H>,
+    sender: ProcessId,
+    args: [u32; 4],
+) -> (i64, Vec<CommitType>, Vec<u8>) {
+    let target = ProcessId(args[0] as u64);
+    let proc = match core.inspect_process(sender, target) {
+        Ok(proc) => proc,
+        Err(e) => return (inspect_error_code(e), Vec::new(), Vec::new()),
+    };
+
+    let m = &proc.metrics;
+    let waiting = m.resumed_ns.is_none() && m.syscall_count > 0;
+    let capabilities = core
+        .cap_spaces
+        .get(&target)
+        .map_or(0, |cspace| cspace.slots.len());
+    let owned = core.endpoints.values().filter(|ep| ep.owner == target);
+    let (endpoints, queued) = owned.fold((0usize, 0usize), |(count, queued), ep| {
+        (count + 1, queued + ep.pending_messages.len())
+    });
+
+    let mut bytes = Vec::with_capacity(7 + 9 * 8 + 3 * 4 + proc.name.len());
+    bytes.push(process_state_to_u8(proc.state));
+    bytes.push(proc.priority.as_u8());
+    bytes.push(waiting as u8);
+    bytes.extend_from_slice(&m.last_syscall.to_le_bytes());
+    for value in [
+        m.memory_size as u64,
+        proc.memory_limit as u64,
+        m.syscall_count,
+        m.ipc_sent,
+        m.ipc_received,
+        m.run_time_ns,
+        m.wakeups,
+        m.last_active_ns,
+    ] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    for count in [capabilities, endpoints, queued] {
+        bytes.extend_from_slice(&(count as u32).to_le_bytes());
+    }
+    bytes.extend_from_slice(proc.name.as_bytes());
+
+    (0, Vec::new(), bytes)
+}
+
+/// Execute process memory read syscall (0x1D).
+///
+/// Returns the number of bytes read, with the bytes as response data.
+pub(in crate::system) fn execute_proc_read_mem<H: HAL>(
+    core: &KernelCore<H>,
+    sender: ProcessId,
+    args: [u32; 4],
+) -> (i64, Vec<CommitType>, Vec<u8>) {
+    let target = ProcessId(args[0] as u64);
+    match core.read_process_memory(sender, target, args[1], args[2]) {
+        Ok(bytes) => (bytes.len() as i64, Vec::new(), bytes),
+        Err(e) => (inspect_error_code(e), Vec::new(), Vec::new()),
+    }
+}
+
+/// Syscall error code for a failed inspection.
+fn inspect_error_code(e: KernelError) -> i64 {
+    let code = match e {
+        KernelError::ProcessNotFound | KernelError::Hal(HalError::ProcessNotFound) => {
+            syscall_error::NOT_FOUND
+        }
+        KernelError::Hal(HalError::InvalidArgument) => syscall_error::INVALID_ARGUMENT,
+        KernelError::Hal(HalError::NotSupported) => syscall_error::NOT_SUPPORTED,
+        _ => syscall_error::PERMISSION_DENIED,
+    };
+    code as i64
+}
@@ -0,0 +1,92 @@
+//! Runtime invariant checks for the System (`debug-invariants` feature)
+//!
+//! See [`crate::invariants`] for what is checked. The System remembers the
+//! SysLog request it is working under, so a commit can be checked against
+//! the syscall (or supervisor request) that caused it.
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::invariants::{Invariant, InvariantViolation};
+use zos_axiom::CommitType;
+use zos_hal::HAL;
+use zos_ipc::pid::{INIT, SUPERVISOR};
+
+use super::System;
+
+impl<H: HAL> System<H> {
+    /// Check on the way into the gateway that kernel state has not changed
+    /// since the last commit.
+    pub(super) fn check_gateway_entry(&self) {
+        if !self.verify_state_digest() {
+            self.invariant_violated(
+                Invariant::NoDirectKernelAccess,
+                String::from("kernel state changed outside the gateway since the last commit"),
+            );
+        }
+    }
+
+    /// Check on the way out of the gateway that the syscall recorded
+    /// everything it changed.
+    pub(super) fn check_gateway_exit(&self) {
+        if !self.verify_state_digest() {
+            self.invariant_violated(
+                Invariant::MutationsAreCommits,
+                String::from("kernel state changed without a matching commit"),
+            );
+        }
+    }
+
+    /// Check a commit against the request it is recorded under.
+    pub(super) fn check_commit(&self, commit_type: &CommitType) {
+        match *commit_type {
+            CommitType::CapGranted { from_pid, .. } => match self.request {
+                Some((sender, _)) if sender.0 == from_pid || sender.0 == SUPERVISOR as u64 => {}
+                Some((sender, _)) => self.invariant_violated(
+                    Invariant::GrantsAreSysLogged,
+                    format!(
+                        "grant from PID {} recorded under a request from PID {}",
+                        from_pid, sender.0
+                    ),
+                ),
+                None => self.invariant_violated(
+                    Invariant::GrantsAreSysLogged,
+                    format!("grant from PID {} has no SysLog request", from_pid),
+                ),
+            },
+            CommitType::ProcessCreated { pid, .. } => {
+                self.check_lifecycle_sender(format!("created process {}", pid))
+            }
+            CommitType::EndpointCreated { id, owner }
+                if self.request.is_some_and(|(sender, _)| sender.0 != owner) =>
+            {
+                self.check_lifecycle_sender(format!("created endpoint {} for PID {}", id, owner))
+            }
+            _ => {}
+        }
+    }
+
+    /// A syscall that did `what` must have come from Init.
+    fn check_lifecycle_sender(&self, what: String) {
+        if let Some((sender, _)) = self.request {
+            if sender.0 != INIT as u64 {
+                self.invariant_violated(Invariant::LifecycleThroughInit, what);
+            }
+        }
+    }
+
+    /// Report a violation to the debug log, then panic with it.
+    fn invariant_violated(&self, invariant: Invariant, detail: String) -> ! {
+        let violation = InvariantViolation {
+            invariant,
+            sender: self.request.map(|(sender, _)| sender),
+            syscall_num: self.request.map(|(_, num)| num),
+            commit_seq: self.axiom.commitlog().current_seq(),
+            detail,
+        };
+        self.kernel
+            .hal()
+            .debug_write(&format!("[kernel] INVARIANT VIOLATION: {}", violation));
+        panic!("{}", violation);
+    }
+}
@@ -3,29 +3,46 @@
 //! This module contains syscall handlers for process lifecycle management:
 //! - `execute_exit()` - Handle process exit syscall
 //! - `execute_kill_with_cap()` - Handle kill process with capability check
+//! - `execute_kill_group()` - Kill a process group with capability check
 //! - `execute_register_process()` - Handle process registration
 //! - `execute_create_endpoint_for()` - Handle endpoint creation for another process
 //! - `execute_load_binary()` - Handle binary loading (Init-only)
 //! - `execute_spawn_process()` - Handle process spawning (Init-only)
+//! - `execute_bind_name()` - Bind a service name to a process (Init-only)
+//! - `execute_set_priority()` - Set a process's priority with capability check
+//! - `execute_set_memory_limit()` - Set a process's memory limit with capability check
+//! - `execute_setpgid()` - Move a process to another process group
 
 use alloc::vec::Vec;
 
 use crate::core::KernelCore;
-use crate::types::ProcessId;
+use crate::error::KernelError;
+use crate::types::{Priority, ProcessId, DEFAULT_MEMORY_LIMIT};
 use zos_axiom::CommitType;
 use zos_hal::{HalError, HAL};
 use zos_ipc::{pid::INIT, syscall_error};
 
 /// Execute process exit syscall (0x11).
 ///
-/// Terminates the calling process and returns its commits.
+/// Terminates the calling process and returns its commits. The exit is
+/// logged with the process's own code, followed by the kill that reaps it,
+/// so replay sees the same exit-then-reap a zombie would go through.
 pub(in crate::system) fn execute_exit<H: HAL>(
     core: &mut KernelCore<H>,
     sender: ProcessId,
+    args: [u32; 4],
     timestamp: u64,
 ) -> (i64, Vec<CommitType>) {
+    let exit_code = args[0] as i32;
     let commits = core.kill_process(sender, timestamp);
-    let commit_types: Vec<CommitType> = commits.into_iter().map(|c| c.commit_type).collect();
+    let mut commit_types = Vec::with_capacity(commits.len() + 1);
+    if let Some(CommitType::ProcessExited { .. }) = commits.first().map(|c| &c.commit_type) {
+        commit_types.push(CommitType::ProcessExited {
+            pid: sender.0,
+            code: exit_code,
+        });
+    }
+    commit_types.extend(commits.into_iter().map(|c| c.commit_type));
     (0, commit_types)
 }
 
@@ -51,13 +68,36 @@ pub(in crate::system) fn execute_kill_with_cap<H: HAL>(
     }
 }
 
+/// Execute kill syscall for a process group (0x13 with `KILL_GROUP`).
+///
+/// # Returns
+/// - On success: the number of processes killed
+/// - On error: a `syscall_error` code
+pub(in crate::system) fn execute_kill_group<H: HAL>(
+    core: &mut KernelCore<H>,
+    sender: ProcessId,
+    args: [u32; 4],
+    timestamp: u64,
+) -> (i64, Vec<CommitType>) {
+    match core.kill_group_with_cap_check(sender, ProcessId(args[0] as u64), timestamp) {
+        (Ok(killed), commits) => (
+            killed as i64,
+            commits.into_iter().map(|c| c.commit_type).collect(),
+        ),
+        (Err(KernelError::ProcessNotFound), _) => (syscall_error::NOT_FOUND as i64, Vec::new()),
+        (Err(_), _) => (syscall_error::PERMISSION_DENIED as i64, Vec::new()),
+    }
+}
+
 /// Execute register process syscall (0x14).
 ///
-/// Creates a new process. Only init (PID 1) can call this.
+/// Creates a new process in the process group in `args[1]`, or in a group
+/// of its own for 0. Only init (PID 1) can call this.
 /// Returns the new process ID or -1 on error.
 pub(in crate::system) fn execute_register_process<H: HAL>(
     core: &mut KernelCore<H>,
     sender: ProcessId,
+    args: [u32; 4],
     data: &[u8],
     timestamp: u64,
 ) -> (i64, Vec<CommitType>) {
@@ -67,7 +107,8 @@ pub(in crate::system) fn execute_register_process<H: HAL>(
     }
 
     let name = core::str::from_utf8(data).unwrap_or("unknown");
-    let (pid, commits) = core.register_process(name, timestamp);
+    let group = (args[1] != 0).then_some(ProcessId(args[1] as u64));
+    let (pid, commits) = core.register_process_in_group(name, group, timestamp);
     let commit_types = commits.into_iter().map(|c| c.commit_type).collect();
 
     (pid.0 as i64, commit_types)
@@ -95,18 +136,20 @@ pub(in crate::system) fn execute_create_endpoint_for<H: HAL>(
     }
 
     let target_pid = ProcessId(args[0] as u64);
-    let (result, mut commits) = core.create_endpoint(target_pid, timestamp);
+    let (result, commits) = core.create_endpoint(target_pid, timestamp);
     let mut commit_types: Vec<CommitType> = commits.into_iter().map(|c| c.commit_type).collect();
 
     match result {
         Ok((eid, _owner_slot)) => {
             // Also grant a capability to Init so it can send to this endpoint
             let init_pid = ProcessId(1);
+            let cap_id = core.next_cap_id();
+            let perms = Permissions::full();
             let cap = Capability {
-                id: core.next_cap_id(),
+                id: cap_id,
                 object_type: ObjectType::Endpoint,
                 object_id: eid.0,
-                permissions: Permissions::full(),
+                permissions: perms,
                 generation: 0,
                 expires_at: 0, // Never expires
             };
@@ -116,6 +159,14 @@ pub(in crate::system) fn execute_create_endpoint_for<H: HAL>(
                 Some(cspace) => cspace.insert(cap),
                 None => return (-1, commit_types),
             };
+            commit_types.push(CommitType::CapInserted {
+                pid: init_pid.0,
+                slot: init_slot,
+                cap_id,
+                object_type: ObjectType::Endpoint as u8,
+                object_id: eid.0,
+                perms: perms.to_byte(),
+            });
             
             // Debug is logged via the returned data - check kernel boot output
             
@@ -219,3 +270,115 @@ pub(in crate::system) fn execute_spawn_process<H: HAL>(
         }
     }
 }
+
+/// Execute bind name syscall (0x18).
+///
+/// Binds a service name to the input endpoint of the process in `args[0]`.
+/// Only init (PID 1) can call this; it does so for every service that
+/// registers with it.
+///
+/// # Returns
+/// - On success: the binding's generation
+/// - On error: a `syscall_error` code
+pub(in crate::system) fn execute_bind_name<H: HAL>(
+    core: &mut KernelCore<H>,
+    sender: ProcessId,
+    args: [u32; 4],
+    data: &[u8],
+) -> i64 {
+    if sender.0 != INIT as u64 {
+        return syscall_error::PERMISSION_DENIED as i64;
+    }
+    let name = match core::str::from_utf8(data) {
+        Ok(n) => n,
+        Err(_) => return syscall_error::INVALID_UTF8 as i64,
+    };
+
+    match core.bind_name(sender, name, ProcessId(args[0] as u64)) {
+        Ok(binding) => binding.generation as i64,
+        Err(KernelError::ProcessNotFound) => syscall_error::NOT_FOUND as i64,
+        Err(_) => syscall_error::INVALID_ARGUMENT as i64,
+    }
+}
+
+/// Execute set priority syscall (0x1A).
+///
+/// Sets the scheduling priority class of the process in `args[0]` to the
+/// `zos_ipc::priority` class in `args[1]`.
+///
+/// # Returns
+/// - On success: 0
+/// - On error: a `syscall_error` code
+pub(in crate::system) fn execute_set_priority<H: HAL>(
+    core: &mut KernelCore<H>,
+    sender: ProcessId,
+    args: [u32; 4],
+    timestamp: u64,
+) -> (i64, Vec<CommitType>) {
+    let priority = match u8::try_from(args[1]).ok().and_then(Priority::from_u8) {
+        Some(p) => p,
+        None => return (syscall_error::INVALID_ARGUMENT as i64, Vec::new()),
+    };
+
+    match core.set_priority_with_cap_check(sender, ProcessId(args[0] as u64), priority, timestamp) {
+        (Ok(()), commits) => (0, commits.into_iter().map(|c| c.commit_type).collect()),
+        (Err(KernelError::ProcessNotFound), _) => (syscall_error::NOT_FOUND as i64, Vec::new()),
+        (Err(_), _) => (syscall_error::PERMISSION_DENIED as i64, Vec::new()),
+    }
+}
+
+/// Execute set memory limit syscall (0x1B).
+///
+/// Sets the memory limit of the process in `args[0]` to `args[1]` KiB, or
+/// back to `DEFAULT_MEMORY_LIMIT` for 0.
+///
+/// # Returns
+/// - On success: 0
+/// - On error: a `syscall_error` code
+pub(in crate::system) fn execute_set_memory_limit<H: HAL>(
+    core: &mut KernelCore<H>,
+    sender: ProcessId,
+    args: [u32; 4],
+    timestamp: u64,
+) -> (i64, Vec<CommitType>) {
+    let limit = match args[1] {
+        0 => DEFAULT_MEMORY_LIMIT,
+        kib => (kib as usize).saturating_mul(1024),
+    };
+
+    let target = ProcessId(args[0] as u64);
+    match core.set_memory_limit_with_cap_check(sender, target, limit, timestamp) {
+        (Ok(()), commits) => (0, commits.into_iter().map(|c| c.commit_type).collect()),
+        (Err(KernelError::ProcessNotFound), _) => (syscall_error::NOT_FOUND as i64, Vec::new()),
+        (Err(KernelError::Hal(HalError::InvalidArgument)), _) => {
+            (syscall_error::INVALID_ARGUMENT as i64, Vec::new())
+        }
+        (Err(_), _) => (syscall_error::PERMISSION_DENIED as i64, Vec::new()),
+    }
+}
+
+/// Execute set process group syscall (0x1E).
+///
+/// Moves the process in `args[0]` to the group in `args[1]`, or to a new
+/// group it leads for 0.
+///
+/// # Returns
+/// - On success: the group ID
+/// - On error: a `syscall_error` code
+pub(in crate::system) fn execute_setpgid<H: HAL>(
+    core: &mut KernelCore<H>,
+    sender: ProcessId,
+    args: [u32; 4],
+    timestamp: u64,
+) -> (i64, Vec<CommitType>) {
+    let target = ProcessId(args[0] as u64);
+    let pgid = ProcessId(args[1] as u64);
+    match core.set_process_group_with_cap_check(sender, target, pgid, timestamp) {
+        (Ok(pgid), commits) => (
+            pgid.0 as i64,
+            commits.into_iter().map(|c| c.commit_type).collect(),
+        ),
+        (Err(KernelError::ProcessNotFound), _) => (syscall_error::NOT_FOUND as i64, Vec::new()),
+        (Err(_), _) => (syscall_error::PERMISSION_DENIED as i64, Vec::new()),
+    }
+}
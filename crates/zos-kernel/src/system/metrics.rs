@@ -8,11 +8,13 @@ use alloc::vec::Vec;
 
 use crate::core::KernelCore;
 use crate::error::KernelError;
-use crate::syscall::{Syscall, SyscallResult};
+use crate::syscall::{SyscallResult, PS_USAGE};
 use crate::types::ProcessId;
 use zos_axiom::CommitType;
 use zos_hal::HAL;
 
+use super::digest::process_state_to_u8;
+
 /// Get rich result and response data for a syscall.
 ///
 /// This function routes syscalls to specialized formatters based on the syscall number.
@@ -27,12 +29,12 @@ pub(in crate::system) fn get_syscall_rich_result<H: HAL>(
     args: [u32; 4],
     _data: &[u8],
     result: i64,
-    timestamp: u64,
+    _timestamp: u64,
 ) -> (SyscallResult, Vec<u8>, Vec<CommitType>) {
     match syscall_num {
-        0x35 => format_caps_list(kernel, sender, result, timestamp), // SYS_CAP_LIST
-        0x50 => format_process_list(kernel, sender, result, timestamp), // SYS_PS
-        0x41 => format_receive_result(kernel, sender, args, result, timestamp),
+        0x35 => format_caps_list(kernel, sender, result), // SYS_CAP_LIST
+        0x50 => format_process_list(kernel, args[0] == PS_USAGE), // SYS_PS
+        0x41 => format_receive_result(result),
         _ => default_rich_result(result),
     }
 }
@@ -46,13 +48,11 @@ pub(in crate::system) fn get_syscall_rich_result<H: HAL>(
 ///   - u8: object type
 ///   - u64: object ID
 pub(in crate::system) fn format_caps_list<H: HAL>(
-    kernel: &mut KernelCore<H>,
+    kernel: &KernelCore<H>,
     sender: ProcessId,
     result: i64,
-    timestamp: u64,
 ) -> (SyscallResult, Vec<u8>, Vec<CommitType>) {
-    let syscall = Syscall::ListCaps;
-    let (rich_result, _) = kernel.handle_syscall(sender, syscall, timestamp);
+    let (rich_result, _) = kernel.handle_list_caps(sender);
 
     if let SyscallResult::CapList(ref caps) = rich_result {
         let mut bytes = Vec::new();
@@ -70,7 +70,7 @@ pub(in crate::system) fn format_caps_list<H: HAL>(
     }
 }
 
-/// Format process list for syscall 0x05 (LIST_PROCESSES).
+/// Format process list for syscall 0x50 (SYS_PS).
 ///
 /// Returns (SyscallResult, response_bytes, commits) where response_bytes contains:
 /// - u32: number of processes
@@ -78,63 +78,54 @@ pub(in crate::system) fn format_caps_list<H: HAL>(
 ///   - u32: process ID
 ///   - u16: name length
 ///   - bytes: process name (UTF-8)
+///   - with `usage`: u8 state, u8 priority, then u64 memory size, syscall
+///     count, messages sent, messages received, run time (nanos) and
+///     wakeups
 pub(in crate::system) fn format_process_list<H: HAL>(
-    kernel: &mut KernelCore<H>,
-    sender: ProcessId,
-    result: i64,
-    timestamp: u64,
+    kernel: &KernelCore<H>,
+    usage: bool,
 ) -> (SyscallResult, Vec<u8>, Vec<CommitType>) {
-    let syscall = Syscall::ListProcesses;
-    let (rich_result, _) = kernel.handle_syscall(sender, syscall, timestamp);
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(kernel.processes.len() as u32).to_le_bytes());
 
-    if let SyscallResult::ProcessList(ref procs) = rich_result {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&(procs.len() as u32).to_le_bytes());
-
-        for (proc_pid, name, _state) in procs {
-            bytes.extend_from_slice(&(proc_pid.0 as u32).to_le_bytes());
-            bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
-            bytes.extend_from_slice(name.as_bytes());
+    for (pid, proc) in &kernel.processes {
+        bytes.extend_from_slice(&(pid.0 as u32).to_le_bytes());
+        bytes.extend_from_slice(&(proc.name.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(proc.name.as_bytes());
+        if usage {
+            let m = &proc.metrics;
+            bytes.push(process_state_to_u8(proc.state));
+            bytes.push(proc.priority.as_u8());
+            for value in [
+                m.memory_size as u64,
+                m.syscall_count,
+                m.ipc_sent,
+                m.ipc_received,
+                m.run_time_ns,
+                m.wakeups,
+            ] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
         }
-
-        (rich_result, bytes, Vec::new())
-    } else {
-        (SyscallResult::Ok(result as u64), Vec::new(), Vec::new())
     }
+
+    let procs = kernel
+        .processes
+        .iter()
+        .map(|(pid, p)| (*pid, p.name.clone(), p.state))
+        .collect();
+    (SyscallResult::ProcessList(procs), bytes, Vec::new())
 }
 
 /// Format IPC receive result for syscall 0x41 (IPC_RECEIVE).
 ///
-/// Returns (SyscallResult, response_bytes, commits) where commits contains
-/// the state changes from actually receiving the message (capability transfers, etc.).
-pub(in crate::system) fn format_receive_result<H: HAL>(
-    kernel: &mut KernelCore<H>,
-    sender: ProcessId,
-    args: [u32; 4],
+/// The message was already received, with its capabilities installed, by
+/// the syscall itself; this only reports the outcome.
+pub(in crate::system) fn format_receive_result(
     result: i64,
-    timestamp: u64,
 ) -> (SyscallResult, Vec<u8>, Vec<CommitType>) {
     if result == 1 {
-        let slot = args[0];
-        let (recv_result, commits) = kernel.ipc_receive_with_caps(sender, slot, timestamp);
-        
-        // Convert commits to CommitTypes so they can be recorded
-        let commit_types: Vec<CommitType> = commits.into_iter().map(|c| c.commit_type).collect();
-
-        match recv_result {
-            Ok(Some((msg, installed_slots))) => {
-                let mut msg_bytes = Vec::new();
-                msg_bytes.extend_from_slice(&(msg.from.0 as u32).to_le_bytes());
-                msg_bytes.extend_from_slice(&msg.tag.to_le_bytes());
-                msg_bytes.push(installed_slots.len() as u8);
-                for cap_slot in &installed_slots {
-                    msg_bytes.extend_from_slice(&cap_slot.to_le_bytes());
-                }
-                msg_bytes.extend_from_slice(&msg.data);
-                (SyscallResult::Message(msg), msg_bytes, commit_types)
-            }
-            _ => (SyscallResult::Ok(result as u64), Vec::new(), commit_types),
-        }
+        (SyscallResult::Ok(result as u64), Vec::new(), Vec::new())
     } else if result == 0 {
         (SyscallResult::WouldBlock, Vec::new(), Vec::new())
     } else {
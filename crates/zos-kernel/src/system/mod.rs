@@ -35,19 +35,35 @@
 //!
 //! All syscalls flow: `Process → System.process_syscall() → Axiom (log) → KernelCore (execute) → Axiom (record) → Process`
 
+mod async_io;
+mod digest;
+mod inspect;
+#[cfg(feature = "debug-invariants")]
+mod invariants;
 mod lifecycle;
 mod metrics;
 
+pub use async_io::{encode_completion, CompletionMessage, STUCK_OP_ERROR};
+
+use alloc::string::String;
 use alloc::vec::Vec;
 
+use crate::call::CallError;
 use crate::capability::Permissions;
+use crate::chaos::{ChaosConfig, FaultInjector, TRANSIENT_SEND_ERROR};
+use crate::core::names::CapRef;
 use crate::core::KernelCore;
 use crate::error::KernelError;
-use crate::ipc::{Endpoint, EndpointDetail, EndpointInfo, Message};
+use crate::graph::ServiceGraph;
+use crate::shm::{ShmAccess, ShmError, ShmId, ShmMapping, ShmRegion};
+use crate::ipc::{BroadcastError, Endpoint, EndpointDetail, EndpointInfo, Message, Subscribers};
+use crate::notification::Notification;
 use crate::syscall::{RevokeNotification, Syscall, SyscallResult};
-use crate::types::{CapSlot, EndpointId, Process, ProcessId, SystemMetrics};
+use crate::types::{
+    CapSlot, EndpointId, NotificationId, Process, ProcessId, SystemMetrics, FAULT_OUT_OF_MEMORY,
+};
 use crate::CapabilitySpace;
-use zos_axiom::{AxiomGateway, Commit, CommitLog, CommitType, SysLog};
+use zos_axiom::{AxiomGateway, Commit, CommitLog, CommitType, StateDigest, SysLog};
 use zos_hal::HAL;
 
 /// System combines the Axiom verification layer with the KernelCore execution layer.
@@ -64,6 +80,16 @@ pub struct System<H: HAL> {
     pub kernel: KernelCore<H>,
     /// Boot time (for uptime calculation)
     boot_time: u64,
+    /// Fault injector (resilience testing only, `None` in normal operation)
+    fault_injector: Option<FaultInjector>,
+    /// Per-object state digest, refreshed on every commit
+    digest: StateDigest,
+    /// Last process whose exit was reported to Init, so reaping it after
+    /// it exited is not reported again
+    reported_exit: Option<u64>,
+    /// Sender and syscall of the SysLog request being served, if any
+    #[cfg(feature = "debug-invariants")]
+    request: Option<(ProcessId, u32)>,
 }
 
 impl<H: HAL> System<H> {
@@ -74,6 +100,11 @@ impl<H: HAL> System<H> {
             axiom: AxiomGateway::new(boot_time),
             kernel: KernelCore::new(hal),
             boot_time,
+            fault_injector: None,
+            digest: StateDigest::new(),
+            reported_exit: None,
+            #[cfg(feature = "debug-invariants")]
+            request: None,
         }
     }
 
@@ -102,8 +133,9 @@ impl<H: HAL> System<H> {
     /// 1. Logs the request to SysLog
     /// 2. Executes via KernelCore
     /// 3. Records commits to CommitLog
-    /// 4. Logs the response to SysLog
-    /// 5. Returns (result_code, rich_result, response_data)
+    /// 4. Accounts the syscall in the sender's metrics
+    /// 5. Logs the response to SysLog
+    /// 6. Returns (result_code, rich_result, response_data)
     ///
     /// # Invariant 9: Axiom Is the Single Syscall Gateway
     ///
@@ -118,19 +150,33 @@ impl<H: HAL> System<H> {
     ) -> (i64, SyscallResult, Vec<u8>) {
         let timestamp = self.uptime_nanos();
 
+        #[cfg(feature = "debug-invariants")]
+        {
+            self.check_gateway_entry();
+            self.request = Some((sender, syscall_num));
+        }
+
         // 1. Log request to SysLog
         let req_id = self
             .axiom
             .syslog_mut()
             .log_request(sender.0, syscall_num, args, timestamp);
 
-        // 2. Execute syscall via KernelCore
-        let (result, commit_types, kernel_response_data) =
-            execute_syscall_kernel_fn(&mut self.kernel, syscall_num, sender, args, data, timestamp);
+        // 2. Execute syscall via KernelCore (unless fault injection fails the send)
+        let (result, commit_types, kernel_response_data) = if syscall_num == crate::SYS_SEND
+            && self
+                .fault_injector
+                .as_mut()
+                .is_some_and(|f| f.should_fail_send())
+        {
+            (TRANSIENT_SEND_ERROR, Vec::new(), Vec::new())
+        } else {
+            execute_syscall_kernel_fn(&mut self.kernel, syscall_num, sender, args, data, timestamp)
+        };
 
         // 3. Record commits to CommitLog
         for ct in commit_types {
-            self.axiom.append_internal_commit(ct, timestamp);
+            self.commit(ct, timestamp);
         }
 
         // 4. Get rich result and response data
@@ -146,10 +192,21 @@ impl<H: HAL> System<H> {
 
         // 5. Record additional commits from formatters (e.g., IPC receive)
         for ct in additional_commits {
-            self.axiom.append_internal_commit(ct, timestamp);
+            self.commit(ct, timestamp);
         }
 
-        // 6. Log response to SysLog
+        #[cfg(feature = "debug-invariants")]
+        {
+            self.request = None;
+            self.check_gateway_exit();
+        }
+
+        // 6. Account the syscall to the sender (volatile metrics, no commit)
+        let returned = self.uptime_nanos();
+        self.kernel
+            .account_syscall(sender, syscall_num, result, timestamp, returned);
+
+        // 7. Log response to SysLog
         self.axiom
             .syslog_mut()
             .log_response(sender.0, req_id, result, timestamp);
@@ -176,6 +233,15 @@ impl<H: HAL> System<H> {
         pid
     }
 
+    /// Register a process in process group `pgid`, or in a group of its own
+    /// without one or if the group has no members left.
+    pub fn register_process_in_group(&mut self, name: &str, pgid: Option<ProcessId>) -> ProcessId {
+        let timestamp = self.uptime_nanos();
+        let (pid, commits) = self.kernel.register_process_in_group(name, pgid, timestamp);
+        self.record_commits(commits, timestamp);
+        pid
+    }
+
     /// Register a process with a specific PID (for supervisor and special processes).
     pub fn register_process_with_pid(&mut self, pid: ProcessId, name: &str) -> ProcessId {
         let timestamp = self.uptime_nanos();
@@ -250,6 +316,8 @@ impl<H: HAL> System<H> {
     // ========================================================================
 
     /// Grant capability and log the mutation.
+    ///
+    /// The grant is SysLogged as a `SYS_CAP_GRANT` from the supervisor.
     pub fn grant_capability(
         &mut self,
         from_pid: ProcessId,
@@ -257,15 +325,17 @@ impl<H: HAL> System<H> {
         to_pid: ProcessId,
         perms: Permissions,
     ) -> Result<CapSlot, KernelError> {
-        let timestamp = self.uptime_nanos();
-        let (result, commits) = self
-            .kernel
-            .grant_capability(from_pid, from_slot, to_pid, perms, timestamp);
-        self.record_commits(commits, timestamp);
-        result
+        let args = [from_slot, to_pid.0 as u32, perms.to_byte() as u32, 0];
+        self.supervisor_request(crate::SYS_CAP_GRANT, args, |system, timestamp| {
+            system
+                .kernel
+                .grant_capability(from_pid, from_slot, to_pid, perms, timestamp)
+        })
     }
 
     /// Grant capability to a specific endpoint directly.
+    ///
+    /// The grant is SysLogged as a `SYS_CAP_GRANT` from the supervisor.
     pub fn grant_capability_to_endpoint(
         &mut self,
         owner_pid: ProcessId,
@@ -273,16 +343,21 @@ impl<H: HAL> System<H> {
         to_pid: ProcessId,
         perms: Permissions,
     ) -> Result<CapSlot, KernelError> {
-        let timestamp = self.uptime_nanos();
-        let (result, commits) = self.kernel.grant_capability_to_endpoint(
-            owner_pid,
-            endpoint_id,
-            to_pid,
-            perms,
-            timestamp,
-        );
-        self.record_commits(commits, timestamp);
-        result
+        let args = [
+            endpoint_id.0 as u32,
+            to_pid.0 as u32,
+            perms.to_byte() as u32,
+            0,
+        ];
+        self.supervisor_request(crate::SYS_CAP_GRANT, args, |system, timestamp| {
+            system.kernel.grant_capability_to_endpoint(
+                owner_pid,
+                endpoint_id,
+                to_pid,
+                perms,
+                timestamp,
+            )
+        })
     }
 
     /// Revoke capability and log the mutation.
@@ -351,6 +426,16 @@ impl<H: HAL> System<H> {
         self.kernel.get_cap_space(pid)
     }
 
+    /// Give a process a Debugger capability and log the mutation.
+    ///
+    /// The supervisor gives PermissionService the only one at spawn.
+    pub fn create_debugger_capability(&mut self, holder: ProcessId) -> Result<CapSlot, KernelError> {
+        let timestamp = self.uptime_nanos();
+        let (result, commits) = self.kernel.create_debugger_cap(holder, timestamp);
+        self.record_commits(commits, timestamp);
+        result
+    }
+
     // ========================================================================
     // IPC Operations
     // ========================================================================
@@ -394,6 +479,10 @@ impl<H: HAL> System<H> {
             tag,
             data: data.to_vec(),
             transferred_caps: alloc::vec![],
+            deadline: None,
+            correlation_id: None,
+            call: None,
+            broadcast: None,
         };
 
         // Queue directly to Init's endpoint (bypasses capability check since kernel is the authority)
@@ -404,7 +493,7 @@ impl<H: HAL> System<H> {
         endpoint.pending_messages.push_back(message);
 
         // Log the injection to CommitLog for audit trail
-        self.axiom.append_internal_commit(
+        self.commit(
             CommitType::MessageSent {
                 from_pid: 0,
                 to_endpoint: init_endpoint.0,
@@ -430,7 +519,7 @@ impl<H: HAL> System<H> {
             .kernel
             .ipc_send(from_pid, endpoint_slot, tag, data, timestamp);
         if let Some(c) = commit {
-            self.axiom.append_internal_commit(c.commit_type, timestamp);
+            self.commit(c.commit_type, timestamp);
         }
         result
     }
@@ -493,6 +582,18 @@ impl<H: HAL> System<H> {
         result
     }
 
+    /// Queue `MSG_TIMER_FIRED` for every process timer that has come due.
+    ///
+    /// Called by the runtime once per scheduling pass. Returns the number
+    /// of messages queued.
+    pub fn fire_timers(&mut self) -> usize {
+        let timestamp = self.uptime_nanos();
+        let commits = self.kernel.fire_timers(timestamp);
+        let fired = commits.len();
+        self.record_commits(commits, timestamp);
+        fired
+    }
+
     // ========================================================================
     // Memory Management
     // ========================================================================
@@ -508,8 +609,120 @@ impl<H: HAL> System<H> {
     }
 
     /// Update process memory size.
-    pub fn update_process_memory(&mut self, pid: ProcessId, new_size: usize) {
-        self.kernel.update_process_memory(pid, new_size)
+    ///
+    /// A process that has grown past its memory limit is terminated: Init
+    /// is sent `MSG_PROCESS_OOM`, then the process is faulted with
+    /// `FAULT_OUT_OF_MEMORY`. Returns whether it was, so the caller can stop
+    /// the process's worker.
+    pub fn update_process_memory(&mut self, pid: ProcessId, new_size: usize) -> bool {
+        let Some(limit) = self.kernel.update_process_memory(pid, new_size) else {
+            return false;
+        };
+
+        let mut payload = [0u8; 20];
+        payload[..4].copy_from_slice(&(pid.0 as u32).to_le_bytes());
+        payload[4..12].copy_from_slice(&(new_size as u64).to_le_bytes());
+        payload[12..].copy_from_slice(&(limit as u64).to_le_bytes());
+        let _ = self.inject_to_init(zos_ipc::init::MSG_PROCESS_OOM, &payload);
+
+        let description = alloc::format!(
+            "out of memory: {} bytes over a limit of {} bytes",
+            new_size,
+            limit
+        );
+        self.fault_process(pid, FAULT_OUT_OF_MEMORY, description);
+        true
+    }
+
+    // ========================================================================
+    // Shared Memory
+    // ========================================================================
+
+    /// Create a shared memory region and log its capability.
+    pub fn shm_create(&mut self, pid: ProcessId, size: u32) -> Result<CapSlot, ShmError> {
+        let timestamp = self.uptime_nanos();
+        let (result, commits) = self.kernel.shm_create(pid, size, timestamp);
+        self.record_commits(commits, timestamp);
+        result
+    }
+
+    /// Check a copy between `pid`'s mapping at `addr` and its region (see
+    /// `SYS_SHM_FLUSH` / `SYS_SHM_FETCH`).
+    pub fn shm_access(
+        &self,
+        pid: ProcessId,
+        addr: u32,
+        offset: u32,
+        len: u32,
+        flush: bool,
+    ) -> Result<ShmAccess, ShmError> {
+        self.kernel.shm_access(pid, addr, offset, len, flush)
+    }
+
+    /// `pid`'s mapping at `addr`.
+    pub fn shm_mapping(&self, pid: ProcessId, addr: u32) -> Option<&ShmMapping> {
+        self.kernel.shm_mapping(pid, addr)
+    }
+
+    /// A live shared memory region.
+    pub fn shm_region(&self, id: ShmId) -> Option<&ShmRegion> {
+        self.kernel.shm_region(id)
+    }
+
+    /// Every live shared memory region.
+    pub fn shm_regions(&self) -> Vec<ShmRegion> {
+        self.kernel.shm_regions()
+    }
+
+    // ========================================================================
+    // Notifications
+    // ========================================================================
+
+    /// Get notification by ID
+    pub fn get_notification(&self, id: NotificationId) -> Option<&Notification> {
+        self.kernel.get_notification(id)
+    }
+
+    /// List all notifications
+    pub fn list_notifications(&self) -> Vec<&Notification> {
+        self.kernel.list_notifications()
+    }
+
+    // ========================================================================
+    // Broadcast Endpoints
+    // ========================================================================
+
+    /// Create a broadcast endpoint and log it with its capability.
+    pub fn create_broadcast(
+        &mut self,
+        owner: ProcessId,
+    ) -> Result<(EndpointId, CapSlot), BroadcastError> {
+        let timestamp = self.uptime_nanos();
+        let (result, commits) = self.kernel.create_broadcast(owner, timestamp);
+        self.record_commits(commits, timestamp);
+        result
+    }
+
+    /// Publish a message to the subscribers of a broadcast endpoint.
+    /// Returns the number it was queued for.
+    pub fn publish(
+        &mut self,
+        pid: ProcessId,
+        slot: CapSlot,
+        tag: u32,
+        data: Vec<u8>,
+    ) -> Result<usize, BroadcastError> {
+        let timestamp = self.uptime_nanos();
+        let (result, commit) = self.kernel.publish(pid, slot, tag, data, timestamp);
+        if let Some(c) = commit {
+            self.commit(c.commit_type, timestamp);
+        }
+        result
+    }
+
+    /// Subscribers of a broadcast endpoint
+    pub fn broadcast_subscribers(&self, id: EndpointId) -> Option<&Subscribers> {
+        self.kernel.broadcast_subscribers(id)
     }
 
     // ========================================================================
@@ -531,6 +744,11 @@ impl<H: HAL> System<H> {
         self.kernel.total_pending_messages()
     }
 
+    /// Snapshot how processes are wired to each other.
+    pub fn service_graph(&self) -> ServiceGraph {
+        self.kernel.service_graph()
+    }
+
     // ========================================================================
     // CommitLog Access
     // ========================================================================
@@ -545,17 +763,130 @@ impl<H: HAL> System<H> {
         self.axiom.syslog()
     }
 
+    /// Get the incremental state digest behind `state_hash()`.
+    pub fn state_digest(&self) -> &StateDigest {
+        &self.digest
+    }
+
+    /// Start a dispatch quantum: until `end_commit_batch`, mutations are
+    /// staged and then recorded as one batch commit.
+    pub fn begin_commit_batch(&mut self) {
+        self.axiom.begin_batch();
+    }
+
+    /// End the dispatch quantum, appending its mutations as one commit.
+    pub fn end_commit_batch(&mut self) {
+        let timestamp = self.uptime_nanos();
+        self.axiom.end_batch(timestamp);
+    }
+
+    // ========================================================================
+    // Fault Injection
+    // ========================================================================
+
+    /// Enable fault injection and record the seed in the CommitLog.
+    ///
+    /// Replaces any active injector; the new seed is recorded either way.
+    pub fn enable_fault_injection(&mut self, seed: u64, config: ChaosConfig) {
+        let timestamp = self.uptime_nanos();
+        self.commit(
+            CommitType::FaultInjectionSeeded {
+                seed,
+                faults: config.faults,
+            },
+            timestamp,
+        );
+        self.fault_injector = Some(FaultInjector::new(seed, config));
+    }
+
+    /// Disable fault injection.
+    pub fn disable_fault_injection(&mut self) {
+        self.fault_injector = None;
+    }
+
+    /// Active fault injector, if any.
+    pub fn fault_injector(&self) -> Option<&FaultInjector> {
+        self.fault_injector.as_ref()
+    }
+
+    /// Active fault injector, for boundary-layer faults (storage, kills).
+    pub fn fault_injector_mut(&mut self) -> Option<&mut FaultInjector> {
+        self.fault_injector.as_mut()
+    }
+
     // ========================================================================
     // Private helpers
     // ========================================================================
 
+    /// Run a direct operation as a SysLogged request from the supervisor,
+    /// recording its commits.
+    fn supervisor_request<T>(
+        &mut self,
+        syscall_num: u32,
+        args: [u32; 4],
+        op: impl FnOnce(&mut Self, u64) -> (Result<T, KernelError>, Vec<Commit>),
+    ) -> Result<T, KernelError> {
+        let timestamp = self.uptime_nanos();
+        let supervisor = zos_ipc::pid::SUPERVISOR as u64;
+        let req_id = self
+            .axiom
+            .syslog_mut()
+            .log_request(supervisor, syscall_num, args, timestamp);
+        #[cfg(feature = "debug-invariants")]
+        {
+            self.request = Some((ProcessId(supervisor), syscall_num));
+        }
+
+        let (result, commits) = op(self, timestamp);
+        self.record_commits(commits, timestamp);
+
+        #[cfg(feature = "debug-invariants")]
+        {
+            self.request = None;
+        }
+        let code = if result.is_ok() { 0 } else { -1 };
+        self.axiom
+            .syslog_mut()
+            .log_response(supervisor, req_id, code, timestamp);
+        result
+    }
+
     /// Record commits to the axiom gateway.
     fn record_commits(&mut self, commits: Vec<Commit>, timestamp: u64) {
         for commit in commits {
-            self.axiom
-                .append_internal_commit(commit.commit_type, timestamp);
+            self.commit(commit.commit_type, timestamp);
         }
     }
+
+    /// Record one applied mutation: update the state digest, then log it.
+    fn commit(&mut self, commit_type: CommitType, timestamp: u64) {
+        #[cfg(feature = "debug-invariants")]
+        self.check_commit(&commit_type);
+        self.refresh_digest(&commit_type);
+        let exited = match commit_type {
+            CommitType::ProcessExited { pid, code }
+                if pid > 1 && self.reported_exit.replace(pid) != Some(pid) =>
+            {
+                Some((pid, code))
+            }
+            _ => None,
+        };
+        self.axiom.append_internal_commit(commit_type, timestamp);
+        if let Some((pid, code)) = exited {
+            self.notify_init_of_exit(pid, code);
+        }
+    }
+
+    /// Tell Init a process is gone so it can restart supervised services.
+    ///
+    /// Before Init has an endpoint there is nobody to tell, so failures
+    /// are dropped.
+    fn notify_init_of_exit(&mut self, pid: u64, code: i32) {
+        let mut payload = [0u8; 8];
+        payload[..4].copy_from_slice(&(pid as u32).to_le_bytes());
+        payload[4..].copy_from_slice(&code.to_le_bytes());
+        let _ = self.inject_to_init(zos_ipc::init::MSG_PROCESS_EXITED, &payload);
+    }
 }
 
 impl<H: HAL + Default> System<H> {
@@ -566,6 +897,11 @@ impl<H: HAL + Default> System<H> {
             kernel: KernelCore::new(hal),
             axiom: AxiomGateway::new(0),
             boot_time: 0,
+            fault_injector: None,
+            digest: StateDigest::new(),
+            reported_exit: None,
+            #[cfg(feature = "debug-invariants")]
+            request: None,
         }
     }
 }
@@ -592,25 +928,26 @@ fn execute_syscall_kernel_fn<H: HAL>(
             let (r, c) = execute_basic_syscall(core, syscall_num, sender, args);
             (r, c, Vec::new())
         }
-        0x11..=0x17 => execute_process_syscall(core, syscall_num, sender, args, data, timestamp),
+        0x08 | 0x09 => (
+            execute_timer_syscall(core, syscall_num, sender, args, timestamp),
+            Vec::new(),
+            Vec::new(),
+        ),
+        0x11..=0x1E => execute_process_syscall(core, syscall_num, sender, args, data, timestamp),
         0x30 | 0x31 | 0x35 => {
             let (r, c) = execute_capability_syscall(core, syscall_num, sender, args, timestamp);
             (r, c, Vec::new())
         }
-        0x40 | 0x41 => {
+        0x40..=0x4F => {
             execute_ipc_syscall(core, syscall_num, sender, args, data, timestamp)
         }
         0x50 => (0, Vec::new(), Vec::new()), // SYS_PS - success, data formatted in metrics.rs
-        0x70..=0x74 => {
-            let (r, c) = execute_storage_syscall(core, syscall_num, sender, data);
-            (r, c, Vec::new())
-        }
-        0x80..=0x84 => {
-            let (r, c) = execute_keystore_syscall(core, syscall_num, sender, data);
+        0x60..=0x64 => {
+            let (r, c) = execute_shm_syscall(core, syscall_num, sender, args, timestamp);
             (r, c, Vec::new())
         }
-        0x90 => {
-            let (r, c) = execute_network_syscall(core, sender, data);
+        0x70..=0x74 | 0x79 | 0x7A | 0x80..=0x84 | 0x90 => {
+            let (r, c) = async_io::execute_async_syscall(core, syscall_num, sender, args, data);
             (r, c, Vec::new())
         }
         _ => (-1, Vec::new(), Vec::new()),
@@ -652,6 +989,37 @@ fn execute_basic_syscall<H: HAL>(
     }
 }
 
+/// Execute a timer syscall (0x08 create, 0x09 cancel).
+///
+/// Arming and cancelling are not commits; each firing is.
+fn execute_timer_syscall<H: HAL>(
+    core: &mut KernelCore<H>,
+    syscall_num: u32,
+    sender: ProcessId,
+    args: [u32; 4],
+    timestamp: u64,
+) -> i64 {
+    const NANOS_PER_MS: u64 = 1_000_000;
+    match syscall_num {
+        0x08 => {
+            let delay = args[0] as u64 * NANOS_PER_MS;
+            let period = (args[1] != 0).then_some(args[1] as u64 * NANOS_PER_MS);
+            match core.create_timer(sender, args[2], delay, period, timestamp) {
+                Ok(id) => id as i64,
+                Err(_) => -1,
+            }
+        }
+        0x09 => {
+            if core.cancel_timer(sender, args[0]) {
+                0
+            } else {
+                -1
+            }
+        }
+        _ => -1,
+    }
+}
+
 fn execute_process_syscall<H: HAL>(
     core: &mut KernelCore<H>,
     syscall_num: u32,
@@ -662,16 +1030,20 @@ fn execute_process_syscall<H: HAL>(
 ) -> (i64, Vec<CommitType>, Vec<u8>) {
     match syscall_num {
         0x11 => {
-            let (r, c) = lifecycle::execute_exit(core, sender, timestamp);
+            let (r, c) = lifecycle::execute_exit(core, sender, args, timestamp);
             (r, c, Vec::new())
         }
         0x12 => (0, Vec::new(), Vec::new()),
+        0x13 if args[1] == zos_ipc::syscall::KILL_GROUP => {
+            let (r, c) = lifecycle::execute_kill_group(core, sender, args, timestamp);
+            (r, c, Vec::new())
+        }
         0x13 => {
             let (r, c) = lifecycle::execute_kill_with_cap(core, sender, args, timestamp);
             (r, c, Vec::new())
         }
         0x14 => {
-            let (r, c) = lifecycle::execute_register_process(core, sender, data, timestamp);
+            let (r, c) = lifecycle::execute_register_process(core, sender, args, data, timestamp);
             (r, c, Vec::new())
         }
         0x15 => {
@@ -683,6 +1055,31 @@ fn execute_process_syscall<H: HAL>(
             let (r, c) = lifecycle::execute_spawn_process(core, sender, data, timestamp);
             (r, c, Vec::new())
         }
+        0x18 => (lifecycle::execute_bind_name(core, sender, args, data), Vec::new(), Vec::new()),
+        0x19 => {
+            let (result, commits) = core.create_broadcast(sender, timestamp);
+            let commit_types: Vec<CommitType> =
+                commits.into_iter().map(|c| c.commit_type).collect();
+            let code = match result {
+                Ok((_, slot)) => slot as i64,
+                Err(e) => broadcast_error_code(e),
+            };
+            (code, commit_types, Vec::new())
+        }
+        0x1A => {
+            let (r, c) = lifecycle::execute_set_priority(core, sender, args, timestamp);
+            (r, c, Vec::new())
+        }
+        0x1B => {
+            let (r, c) = lifecycle::execute_set_memory_limit(core, sender, args, timestamp);
+            (r, c, Vec::new())
+        }
+        0x1C => inspect::execute_proc_state(core, sender, args),
+        0x1D => inspect::execute_proc_read_mem(core, sender, args),
+        0x1E => {
+            let (r, c) = lifecycle::execute_setpgid(core, sender, args, timestamp);
+            (r, c, Vec::new())
+        }
         _ => (-1, Vec::new(), Vec::new()),
     }
 }
@@ -747,265 +1144,337 @@ fn execute_ipc_syscall<H: HAL>(
         0x40 => {
             let slot = args[0];
             let tag = args[1];
-            let (result, commit) = core.ipc_send(sender, slot, tag, data.to_vec(), timestamp);
+            let payload = core.message_pool.copy_of(data);
+            let (result, commit) = core.ipc_send(sender, slot, tag, payload, timestamp);
             let commit_types: Vec<CommitType> = commit.into_iter().map(|c| c.commit_type).collect();
-            match result {
-                Ok(()) => (0, commit_types, Vec::new()),
-                Err(_) => (-1, commit_types, Vec::new()),
-            }
+            (send_result_code(result), commit_types, Vec::new())
         }
         0x41 => {
-            // SYS_RECV: Actually receive and return message data
+            // SYS_RECV: receive the message, installing any capabilities it
+            // carries in the receiver's CSpace
             let slot = args[0];
-            match core.ipc_receive(sender, slot, timestamp) {
-                Ok(Some(msg)) => {
+            let (result, commits) = core.ipc_receive_with_caps(sender, slot, timestamp);
+            let commit_types: Vec<CommitType> =
+                commits.into_iter().map(|c| c.commit_type).collect();
+            match result {
+                Ok(Some((msg, installed_slots))) => {
                     // Serialize the message for return to the caller
-                    let response_data = serialize_ipc_message(&msg);
+                    let response_data = serialize_ipc_message(&msg, installed_slots);
+                    // The payload has been copied out; keep its buffer for the next send
+                    core.message_pool.give(msg.data);
                     // Return 1 to indicate message received, with serialized message data
-                    (1, Vec::new(), response_data)
+                    (1, commit_types, response_data)
                 }
-                Ok(None) => (0, Vec::new(), Vec::new()),
-                Err(_) => (-1, Vec::new(), Vec::new()),
+                Ok(None) => (0, commit_types, Vec::new()),
+                Err(_) => (-1, commit_types, Vec::new()),
+            }
+        }
+        0x42 => {
+            let (r, c) = execute_call(core, sender, args, data, timestamp);
+            (r, c, Vec::new())
+        }
+        0x43 => {
+            let payload = core.message_pool.copy_of(data);
+            let caller = ProcessId(args[0] as u64);
+            match core.ipc_reply(sender, caller, args[1], payload, timestamp) {
+                Ok(()) => (0, Vec::new(), Vec::new()),
+                Err(_) => (zos_ipc::syscall_error::NOT_FOUND as i64, Vec::new(), Vec::new()),
+            }
+        }
+        0x44 => {
+            let (r, c) = execute_send_with_caps(core, sender, args, data, timestamp);
+            (r, c, Vec::new())
+        }
+        0x45 => {
+            let (r, c) = execute_send_named(core, sender, args, data, timestamp);
+            (r, c, Vec::new())
+        }
+        0x46 => {
+            let deadline = ((args[1] as u64) << 32) | args[0] as u64;
+            core.set_deadline(sender, (deadline != 0).then_some(deadline));
+            (0, Vec::new(), Vec::new())
+        }
+        0x47 => {
+            let deadline = core.deadline(sender).unwrap_or(0);
+            let result = if args[0] == 0 {
+                (deadline & 0xFFFFFFFF) as i64
+            } else {
+                ((deadline >> 32) & 0xFFFFFFFF) as i64
+            };
+            (result, Vec::new(), Vec::new())
+        }
+        0x48 => {
+            core.set_correlation_id(sender, (args[0] != 0).then_some(args[0]));
+            (0, Vec::new(), Vec::new())
+        }
+        0x49 => (
+            core.correlation_id(sender).unwrap_or(0) as i64,
+            Vec::new(),
+            Vec::new(),
+        ),
+        0x4A => match core.call_wait(sender, timestamp) {
+            Ok(Some(reply)) => {
+                let response_data = serialize_ipc_message(&reply, Vec::new());
+                core.message_pool.give(reply.data);
+                (1, Vec::new(), response_data)
             }
+            Ok(None) => (0, Vec::new(), Vec::new()),
+            Err(e) => (call_error_code(e), Vec::new(), Vec::new()),
+        },
+        0x4B => {
+            let (result, commits) = core.create_notification(sender, timestamp);
+            let commit_types: Vec<CommitType> =
+                commits.into_iter().map(|c| c.commit_type).collect();
+            let code = match result {
+                Ok((_, slot)) => slot as i64,
+                Err(KernelError::PermissionDenied) => zos_ipc::syscall_error::LIMIT_EXCEEDED as i64,
+                Err(_) => -1,
+            };
+            (code, commit_types, Vec::new())
+        }
+        0x4C => {
+            if args[1] == 0 || args[1] & !zos_ipc::syscall::NOTIFY_BITS != 0 {
+                return (
+                    zos_ipc::syscall_error::INVALID_ARGUMENT as i64,
+                    Vec::new(),
+                    Vec::new(),
+                );
+            }
+            let (result, commit) = core.signal_notification(sender, args[0], args[1], timestamp);
+            let commit_types: Vec<CommitType> = commit.into_iter().map(|c| c.commit_type).collect();
+            let code = match result {
+                Ok(()) => 0,
+                Err(e) => notification_error_code(e),
+            };
+            (code, commit_types, Vec::new())
+        }
+        0x4D => {
+            let code = match core.wait_notification(sender, args[0], timestamp) {
+                Ok(bits) => bits as i64,
+                Err(e) => notification_error_code(e),
+            };
+            (code, Vec::new(), Vec::new())
+        }
+        0x4E => {
+            let result = if args[1] == zos_ipc::syscall::UNSUBSCRIBE {
+                core.unsubscribe(sender, args[0], timestamp)
+            } else {
+                core.subscribe(sender, args[0], args[1], timestamp)
+            };
+            let code = result.map_or_else(broadcast_error_code, |()| 0);
+            (code, Vec::new(), Vec::new())
+        }
+        0x4F => {
+            let payload = core.message_pool.copy_of(data);
+            let (result, commit) = core.publish(sender, args[0], args[1], payload, timestamp);
+            let commit_types: Vec<CommitType> = commit.into_iter().map(|c| c.commit_type).collect();
+            let code = match result {
+                Ok(delivered) => delivered as i64,
+                Err(e) => broadcast_error_code(e),
+            };
+            (code, commit_types, Vec::new())
         }
         _ => (-1, Vec::new(), Vec::new()),
     }
 }
 
-/// Serialize an IPC message for syscall response
-/// Format: [from_pid: u32 LE][tag: u32 LE][num_caps: u8][cap_slots: u32 LE * num_caps][data: [u8]]
-fn serialize_ipc_message(msg: &crate::ipc::Message) -> Vec<u8> {
-    let num_caps = msg.transferred_caps.len() as u8;
-    let cap_data_len = (num_caps as usize) * 4;
-    let mut buf = Vec::with_capacity(9 + cap_data_len + msg.data.len());
-    
-    // from_pid as u32
-    buf.extend_from_slice(&(msg.from.0 as u32).to_le_bytes());
-    // tag as u32
-    buf.extend_from_slice(&msg.tag.to_le_bytes());
-    // num_caps as u8
-    buf.push(num_caps);
-    // cap_slots as u32 each (receiver_slot hint, or 0 if not specified)
-    for cap in &msg.transferred_caps {
-        let slot = cap.receiver_slot.unwrap_or(0);
-        buf.extend_from_slice(&slot.to_le_bytes());
-    }
-    // data
-    buf.extend_from_slice(&msg.data);
-    buf
-}
-
-fn execute_storage_syscall<H: HAL>(
-    core: &KernelCore<H>,
-    syscall_num: u32,
-    sender: ProcessId,
-    data: &[u8],
-) -> (i64, Vec<CommitType>) {
-    match syscall_num {
-        0x70 => execute_storage_read(core, sender, data),
-        0x71 => execute_storage_write(core, sender, data),
-        0x72 => execute_storage_delete(core, sender, data),
-        0x73 => execute_storage_list(core, sender, data),
-        0x74 => execute_storage_exists(core, sender, data),
-        _ => (-1, Vec::new()),
-    }
-}
-
-fn execute_storage_read<H: HAL>(
-    core: &KernelCore<H>,
-    sender: ProcessId,
-    data: &[u8],
-) -> (i64, Vec<CommitType>) {
-    let key = match core::str::from_utf8(data) {
-        Ok(k) => k,
-        Err(_) => return (-1, Vec::new()),
-    };
-    match core.hal().storage_read_async(sender.0, key) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
-    }
-}
-
-fn execute_storage_write<H: HAL>(
-    core: &KernelCore<H>,
-    sender: ProcessId,
-    data: &[u8],
-) -> (i64, Vec<CommitType>) {
-    if data.len() < 4 {
-        return (-1, Vec::new());
-    }
-    let key_len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
-    if data.len() < 4 + key_len {
-        return (-1, Vec::new());
-    }
-    let key = match core::str::from_utf8(&data[4..4 + key_len]) {
-        Ok(k) => k,
-        Err(_) => return (-1, Vec::new()),
+/// Syscall return value for a failed signal or wait.
+fn notification_error_code(error: KernelError) -> i64 {
+    use zos_ipc::syscall_error;
+    let code = match error {
+        KernelError::PermissionDenied => syscall_error::PERMISSION_DENIED,
+        _ => syscall_error::NOT_FOUND,
     };
-    let value = &data[4 + key_len..];
-    match core.hal().storage_write_async(sender.0, key, value) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
-    }
+    code as i64
 }
 
-fn execute_storage_delete<H: HAL>(
-    core: &KernelCore<H>,
-    sender: ProcessId,
-    data: &[u8],
-) -> (i64, Vec<CommitType>) {
-    let key = match core::str::from_utf8(data) {
-        Ok(k) => k,
-        Err(_) => return (-1, Vec::new()),
+/// Syscall return value for a failed broadcast operation.
+fn broadcast_error_code(error: BroadcastError) -> i64 {
+    use zos_ipc::syscall_error;
+    let code = match error {
+        BroadcastError::Capability(KernelError::PermissionDenied) => {
+            syscall_error::PERMISSION_DENIED
+        }
+        BroadcastError::Capability(_) => syscall_error::NOT_FOUND,
+        BroadcastError::WrongKind | BroadcastError::TooLarge => syscall_error::INVALID_ARGUMENT,
+        BroadcastError::LimitExceeded => syscall_error::LIMIT_EXCEEDED,
     };
-    match core.hal().storage_delete_async(sender.0, key) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
-    }
+    code as i64
 }
 
-fn execute_storage_list<H: HAL>(
-    core: &KernelCore<H>,
+/// Execute a shared memory syscall (0x60 create, 0x61 map, 0x62 unmap,
+/// 0x63 flush, 0x64 fetch).
+///
+/// Flush and fetch only check the copy; the supervisor does it once they
+/// succeed, as the region's bytes live outside the kernel.
+fn execute_shm_syscall<H: HAL>(
+    core: &mut KernelCore<H>,
+    syscall_num: u32,
     sender: ProcessId,
-    data: &[u8],
+    args: [u32; 4],
+    timestamp: u64,
 ) -> (i64, Vec<CommitType>) {
-    let prefix = match core::str::from_utf8(data) {
-        Ok(p) => p,
-        Err(_) => return (-1, Vec::new()),
+    let result = match syscall_num {
+        0x60 => {
+            let (result, commits) = core.shm_create(sender, args[0], timestamp);
+            let commit_types: Vec<CommitType> =
+                commits.into_iter().map(|c| c.commit_type).collect();
+            return match result {
+                Ok(slot) => (slot as i64, commit_types),
+                Err(e) => (shm_error_code(e), commit_types),
+            };
+        }
+        0x61 => core
+            .shm_map(sender, args[0], args[1], args[2], timestamp)
+            .map(|len| len as i64),
+        0x62 => core.shm_unmap(sender, args[0]).map(|()| 0),
+        0x63 | 0x64 => core
+            .shm_access(sender, args[0], args[1], args[2], syscall_num == 0x63)
+            .map(|_| 0),
+        _ => return (-1, Vec::new()),
     };
-    match core.hal().storage_list_async(sender.0, prefix) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
-    }
+    (result.unwrap_or_else(shm_error_code), Vec::new())
 }
 
-fn execute_storage_exists<H: HAL>(
-    core: &KernelCore<H>,
-    sender: ProcessId,
-    data: &[u8],
-) -> (i64, Vec<CommitType>) {
-    let key = match core::str::from_utf8(data) {
-        Ok(k) => k,
-        Err(_) => return (-1, Vec::new()),
+/// Syscall return value for a failed shared memory operation.
+fn shm_error_code(error: ShmError) -> i64 {
+    use zos_ipc::syscall_error;
+    let code = match error {
+        ShmError::Capability(KernelError::PermissionDenied) | ShmError::ReadOnly => {
+            syscall_error::PERMISSION_DENIED
+        }
+        ShmError::Capability(_) | ShmError::NoRegion | ShmError::NotMapped => {
+            syscall_error::NOT_FOUND
+        }
+        ShmError::InvalidSize | ShmError::Overlap | ShmError::OutOfRange => {
+            syscall_error::INVALID_ARGUMENT
+        }
+        ShmError::LimitExceeded => syscall_error::LIMIT_EXCEEDED,
     };
-    match core.hal().storage_exists_async(sender.0, key) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
-    }
-}
-
-// ============================================================================
-// Keystore Syscalls (0x80-0x84)
-// ============================================================================
-
-fn execute_keystore_syscall<H: HAL>(
-    core: &KernelCore<H>,
-    syscall_num: u32,
-    sender: ProcessId,
-    data: &[u8],
-) -> (i64, Vec<CommitType>) {
-    match syscall_num {
-        0x80 => execute_keystore_read(core, sender, data),
-        0x81 => execute_keystore_write(core, sender, data),
-        0x82 => execute_keystore_delete(core, sender, data),
-        0x83 => execute_keystore_list(core, sender, data),
-        0x84 => execute_keystore_exists(core, sender, data),
-        _ => (-1, Vec::new()),
-    }
+    code as i64
 }
 
-fn execute_keystore_read<H: HAL>(
-    core: &KernelCore<H>,
+/// Execute send-with-capabilities syscall (0x44).
+///
+/// args[0] is the endpoint slot, args[1] the tag and args[2] the data
+/// length in its low 16 bits and the capability count above them. The
+/// payload is the data followed by the capability slots (u32 LE each).
+fn execute_send_with_caps<H: HAL>(
+    core: &mut KernelCore<H>,
     sender: ProcessId,
+    args: [u32; 4],
     data: &[u8],
+    timestamp: u64,
 ) -> (i64, Vec<CommitType>) {
-    let key = match core::str::from_utf8(data) {
-        Ok(k) => k,
-        Err(_) => return (-1, Vec::new()),
-    };
-    match core.hal().keystore_read_async(sender.0, key) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
+    let data_len = (args[2] & 0xFFFF) as usize;
+    let cap_count = (args[2] >> 16) as usize;
+    if data.len() != data_len + cap_count * 4 {
+        return (-1, Vec::new());
     }
+    let (payload, slots) = data.split_at(data_len);
+    let cap_slots: Vec<CapSlot> = slots
+        .as_chunks::<4>()
+        .0
+        .iter()
+        .map(|b| u32::from_le_bytes(*b))
+        .collect();
+
+    let payload = core.message_pool.copy_of(payload);
+    let (result, commits) =
+        core.ipc_send_with_caps(sender, args[0], args[1], payload, &cap_slots, timestamp);
+    let commit_types: Vec<CommitType> = commits.into_iter().map(|c| c.commit_type).collect();
+    (send_result_code(result), commit_types)
 }
 
-fn execute_keystore_write<H: HAL>(
-    core: &KernelCore<H>,
+/// Execute send-by-name syscall (0x45).
+///
+/// Payload: [name_len: u8, name: [u8], data: [u8]]; args[0] is the tag.
+/// Commits include any capability the name resolution created or deleted,
+/// even when the send itself fails.
+fn execute_send_named<H: HAL>(
+    core: &mut KernelCore<H>,
     sender: ProcessId,
+    args: [u32; 4],
     data: &[u8],
+    timestamp: u64,
 ) -> (i64, Vec<CommitType>) {
-    if data.len() < 4 {
+    let Some((&name_len, rest)) = data.split_first() else {
         return (-1, Vec::new());
-    }
-    let key_len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
-    if data.len() < 4 + key_len {
+    };
+    if rest.len() < name_len as usize {
         return (-1, Vec::new());
     }
-    let key = match core::str::from_utf8(&data[4..4 + key_len]) {
-        Ok(k) => k,
+    let (name, payload) = rest.split_at(name_len as usize);
+    let name = match core::str::from_utf8(name) {
+        Ok(n) => n,
         Err(_) => return (-1, Vec::new()),
     };
-    let value = &data[4 + key_len..];
-    match core.hal().keystore_write_async(sender.0, key, value) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
-    }
+
+    let cap_ref = CapRef::Named(String::from(name));
+    let payload = core.message_pool.copy_of(payload);
+    let (result, commits) = core.ipc_send_ref(sender, &cap_ref, args[0], payload, timestamp);
+    let commit_types: Vec<CommitType> = commits.into_iter().map(|c| c.commit_type).collect();
+    (send_result_code(result), commit_types)
 }
 
-fn execute_keystore_delete<H: HAL>(
-    core: &KernelCore<H>,
+/// Execute call syscall (0x42).
+///
+/// args[0] is the endpoint slot, args[1] the tag and args[2] the timeout in
+/// milliseconds (0 for none); the payload is the request.
+fn execute_call<H: HAL>(
+    core: &mut KernelCore<H>,
     sender: ProcessId,
+    args: [u32; 4],
     data: &[u8],
+    timestamp: u64,
 ) -> (i64, Vec<CommitType>) {
-    let key = match core::str::from_utf8(data) {
-        Ok(k) => k,
-        Err(_) => return (-1, Vec::new()),
+    let timeout = (args[2] != 0).then(|| args[2] as u64 * 1_000_000);
+    let payload = core.message_pool.copy_of(data);
+    let (result, commit) = core.ipc_call(sender, args[0], args[1], payload, timeout, timestamp);
+    let commit_types: Vec<CommitType> = commit.into_iter().map(|c| c.commit_type).collect();
+    let code = match result {
+        Ok(_) => 0,
+        Err(CallError::Send(e)) => send_result_code(Err(e)),
+        Err(e) => call_error_code(e),
     };
-    match core.hal().keystore_delete_async(sender.0, key) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
-    }
+    (code, commit_types)
 }
 
-fn execute_keystore_list<H: HAL>(
-    core: &KernelCore<H>,
-    sender: ProcessId,
-    data: &[u8],
-) -> (i64, Vec<CommitType>) {
-    let prefix = match core::str::from_utf8(data) {
-        Ok(p) => p,
-        Err(_) => return (-1, Vec::new()),
+/// Syscall return value for a failed call.
+fn call_error_code(error: CallError) -> i64 {
+    use zos_ipc::syscall_error;
+    let code = match error {
+        CallError::Busy => syscall_error::BUSY,
+        CallError::TimedOut => syscall_error::TIMED_OUT,
+        CallError::PeerDied => syscall_error::PEER_DIED,
+        CallError::NoCall => syscall_error::NOT_FOUND,
+        CallError::Send(_) => return -1,
     };
-    match core.hal().keystore_list_async(sender.0, prefix) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
-    }
+    code as i64
 }
 
-fn execute_keystore_exists<H: HAL>(
-    core: &KernelCore<H>,
-    sender: ProcessId,
-    data: &[u8],
-) -> (i64, Vec<CommitType>) {
-    let key = match core::str::from_utf8(data) {
-        Ok(k) => k,
-        Err(_) => return (-1, Vec::new()),
-    };
-    match core.hal().keystore_exists_async(sender.0, key) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
+/// Syscall return value for a send: 0, `WOULD_BLOCK` when the endpoint is
+/// full (the sender is now in line for it), or -1.
+fn send_result_code(result: Result<(), KernelError>) -> i64 {
+    match result {
+        Ok(()) => 0,
+        Err(KernelError::WouldBlock) => zos_ipc::syscall_error::WOULD_BLOCK as i64,
+        Err(_) => -1,
     }
 }
 
-fn execute_network_syscall<H: HAL>(
-    core: &KernelCore<H>,
-    sender: ProcessId,
-    data: &[u8],
-) -> (i64, Vec<CommitType>) {
-    match core.hal().network_fetch_async(sender.0, data) {
-        Ok(request_id) => (request_id as i64, Vec::new()),
-        Err(_) => (-1, Vec::new()),
-    }
+/// Serialize an IPC message for syscall response, in the
+/// `zos_ipc::frame` format, with the slots its capabilities were installed
+/// in
+fn serialize_ipc_message(msg: &crate::ipc::Message, cap_slots: Vec<CapSlot>) -> Vec<u8> {
+    zos_ipc::frame::Frame {
+        from_pid: msg.from.0 as u32,
+        tag: msg.tag,
+        cap_slots,
+        correlation_id: msg.correlation_id,
+        data: &msg.data,
+    }
+    .encode()
 }
 
 #[cfg(test)]
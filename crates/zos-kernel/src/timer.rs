@@ -0,0 +1,279 @@
+//! Kernel timers
+//!
+//! A process arms a timer with `SYS_TIMER_CREATE`, naming one of its own
+//! endpoints. When the timer comes due the kernel queues
+//! `MSG_TIMER_FIRED` on that endpoint, from PID 0, carrying the timer ID.
+//! A periodic timer is then rearmed one period on; a one-shot timer is
+//! gone. Timers belong to their process and are cancelled when it exits.
+//!
+//! Timers live in a hashed timing wheel: [`WHEEL_SLOTS`] buckets of
+//! [`TICK_NANOS`] each, a timer going in the bucket its due time falls in.
+//! Each expiry pass visits only the buckets for the ticks elapsed since the
+//! last pass, so its cost depends on the timers due, not on all armed
+//! timers. A bucket also holds timers due on a later turn of the wheel;
+//! they stay put until their turn comes.
+//!
+//! Firing is best effort: a firing is skipped while the previous one of
+//! the same timer is still queued, or the endpoint is full.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::types::{EndpointId, ProcessId};
+
+/// Wheel resolution: timers fire at most this late.
+pub const TICK_NANOS: u64 = 10_000_000;
+
+/// Buckets in the wheel (one revolution is `WHEEL_SLOTS * TICK_NANOS`).
+pub const WHEEL_SLOTS: usize = 256;
+
+/// Maximum armed timers per process (DoS protection).
+pub const MAX_TIMERS_PER_PROCESS: usize = 32;
+
+/// Shortest period of a periodic timer; shorter ones are rounded up.
+pub const MIN_PERIOD_NANOS: u64 = TICK_NANOS;
+
+/// Timer identifier, unique since boot.
+pub type TimerId = u32;
+
+/// An armed timer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timer {
+    pub id: TimerId,
+    /// Process that armed it
+    pub owner: ProcessId,
+    /// Endpoint `MSG_TIMER_FIRED` is queued on
+    pub endpoint: EndpointId,
+    /// Uptime (nanos) it next fires at
+    pub due: u64,
+    /// Rearm interval, for a periodic timer
+    pub period: Option<u64>,
+}
+
+/// Why a timer could not be armed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerError {
+    /// The process already has [`MAX_TIMERS_PER_PROCESS`] timers
+    TooManyTimers,
+}
+
+/// Hashed timing wheel holding every armed timer.
+#[derive(Debug)]
+pub struct TimerWheel {
+    timers: BTreeMap<TimerId, Timer>,
+    /// Timer IDs by bucket; IDs of cancelled timers are dropped lazily
+    slots: Vec<Vec<TimerId>>,
+    /// Last tick visited by `expire`
+    tick: u64,
+    next_id: TimerId,
+}
+
+impl Default for TimerWheel {
+    fn default() -> Self {
+        Self {
+            timers: BTreeMap::new(),
+            slots: vec![Vec::new(); WHEEL_SLOTS],
+            tick: 0,
+            next_id: 1,
+        }
+    }
+}
+
+impl TimerWheel {
+    /// Arm a timer firing `delay` nanos after `now`, then every `period`
+    /// nanos if given.
+    pub fn insert(
+        &mut self,
+        owner: ProcessId,
+        endpoint: EndpointId,
+        now: u64,
+        delay: u64,
+        period: Option<u64>,
+    ) -> Result<TimerId, TimerError> {
+        if self.count_of(owner) >= MAX_TIMERS_PER_PROCESS {
+            return Err(TimerError::TooManyTimers);
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        self.schedule(Timer {
+            id,
+            owner,
+            endpoint,
+            due: now.saturating_add(delay),
+            period: period.map(|p| p.max(MIN_PERIOD_NANOS)),
+        });
+        Ok(id)
+    }
+
+    /// Cancel `owner`'s timer `id`. Returns whether there was one.
+    pub fn cancel(&mut self, owner: ProcessId, id: TimerId) -> bool {
+        match self.timers.get(&id) {
+            Some(timer) if timer.owner == owner => {
+                self.timers.remove(&id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Cancel every timer of `owner` (it exited).
+    pub fn cancel_all(&mut self, owner: ProcessId) {
+        self.timers.retain(|_, timer| timer.owner != owner);
+    }
+
+    /// Look up an armed timer.
+    pub fn get(&self, id: TimerId) -> Option<&Timer> {
+        self.timers.get(&id)
+    }
+
+    /// Number of timers `owner` has armed.
+    pub fn count_of(&self, owner: ProcessId) -> usize {
+        self.timers.values().filter(|t| t.owner == owner).count()
+    }
+
+    /// Number of armed timers.
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    /// Whether no timers are armed.
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Take the timers due at `now`, in firing order, rearming the
+    /// periodic ones. A periodic timer that missed several periods fires
+    /// once.
+    pub fn expire(&mut self, now: u64) -> Vec<Timer> {
+        let target = now / TICK_NANOS;
+        let visits = (target.saturating_sub(self.tick) + 1).min(WHEEL_SLOTS as u64);
+        let mut fired = Vec::new();
+        for tick in self.tick..self.tick + visits {
+            let slot = (tick % WHEEL_SLOTS as u64) as usize;
+            let timers = &self.timers;
+            let (due, waiting): (Vec<TimerId>, Vec<TimerId>) = self.slots[slot]
+                .iter()
+                .copied()
+                .filter(|id| timers.contains_key(id))
+                .partition(|id| timers[id].due <= now);
+            self.slots[slot] = waiting;
+            fired.extend(due.iter().map(|id| self.timers[id]));
+        }
+        self.tick = self.tick.max(target);
+
+        fired.sort_by_key(|timer| (timer.due, timer.id));
+        for timer in &fired {
+            match timer.period {
+                Some(period) => {
+                    let missed = (now - timer.due) / period;
+                    self.schedule(Timer {
+                        due: timer.due + (missed + 1) * period,
+                        ..*timer
+                    });
+                }
+                None => {
+                    self.timers.remove(&timer.id);
+                }
+            }
+        }
+        fired
+    }
+
+    /// Put `timer` in the bucket of its due time.
+    fn schedule(&mut self, timer: Timer) {
+        // A timer due in a tick already visited goes in the current one
+        let tick = (timer.due / TICK_NANOS).max(self.tick);
+        self.slots[(tick % WHEEL_SLOTS as u64) as usize].push(timer.id);
+        self.timers.insert(timer.id, timer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: u64 = 1_000_000;
+
+    fn arm(wheel: &mut TimerWheel, now: u64, delay: u64, period: Option<u64>) -> TimerId {
+        wheel
+            .insert(ProcessId(5), EndpointId(9), now, delay, period)
+            .unwrap()
+    }
+
+    fn ids(timers: Vec<Timer>) -> Vec<TimerId> {
+        timers.into_iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn test_one_shot_fires_once() {
+        let mut wheel = TimerWheel::default();
+        let id = arm(&mut wheel, 0, 25 * MS, None);
+        assert!(wheel.expire(24 * MS).is_empty());
+        assert_eq!(ids(wheel.expire(25 * MS)), [id]);
+        assert!(wheel.expire(100 * MS).is_empty());
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn test_periodic_rearms_and_coalesces_missed_periods() {
+        let mut wheel = TimerWheel::default();
+        let id = arm(&mut wheel, 0, 10 * MS, Some(20 * MS));
+        assert_eq!(ids(wheel.expire(10 * MS)), [id]);
+        assert_eq!(wheel.get(id).unwrap().due, 30 * MS);
+
+        // Asleep for several periods: one firing, next due on the period grid
+        assert_eq!(ids(wheel.expire(95 * MS)), [id]);
+        assert_eq!(wheel.get(id).unwrap().due, 110 * MS);
+        assert!(wheel.expire(100 * MS).is_empty());
+    }
+
+    #[test]
+    fn test_timers_beyond_one_revolution_wait_their_turn() {
+        let mut wheel = TimerWheel::default();
+        let revolution = WHEEL_SLOTS as u64 * TICK_NANOS;
+        let late = arm(&mut wheel, 0, revolution + 5 * MS, None);
+        let early = arm(&mut wheel, 0, 5 * MS, None);
+        assert_eq!(ids(wheel.expire(10 * MS)), [early]);
+        assert!(wheel.expire(revolution).is_empty());
+        assert_eq!(ids(wheel.expire(revolution + 5 * MS)), [late]);
+    }
+
+    #[test]
+    fn test_long_gap_visits_every_bucket() {
+        let mut wheel = TimerWheel::default();
+        let a = arm(&mut wheel, 0, 30 * MS, None);
+        let b = arm(&mut wheel, 0, 20 * MS, None);
+        // Fired in due order, whatever the buckets were visited in
+        assert_eq!(ids(wheel.expire(1_000_000 * MS)), [b, a]);
+    }
+
+    #[test]
+    fn test_cancel_and_limits() {
+        let mut wheel = TimerWheel::default();
+        let id = arm(&mut wheel, 0, 10 * MS, None);
+        assert!(!wheel.cancel(ProcessId(6), id), "only the owner cancels");
+        assert!(wheel.cancel(ProcessId(5), id));
+        assert!(!wheel.cancel(ProcessId(5), id));
+        assert!(wheel.expire(20 * MS).is_empty());
+
+        for _ in 0..MAX_TIMERS_PER_PROCESS {
+            arm(&mut wheel, 0, MS, Some(1));
+        }
+        assert_eq!(
+            wheel.insert(ProcessId(5), EndpointId(9), 0, MS, None),
+            Err(TimerError::TooManyTimers)
+        );
+        assert!(wheel
+            .insert(ProcessId(6), EndpointId(9), 0, 50 * MS, None)
+            .is_ok());
+        // Periods are rounded up to the wheel's resolution
+        assert!(wheel
+            .expire(MS)
+            .iter()
+            .all(|t| t.period == Some(MIN_PERIOD_NANOS)));
+
+        wheel.cancel_all(ProcessId(5));
+        assert_eq!(wheel.len(), 1);
+    }
+}
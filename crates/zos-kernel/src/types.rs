@@ -2,22 +2,30 @@
 //!
 //! This module contains the fundamental types used throughout the kernel:
 //! - Process and endpoint identifiers
-//! - Process state and metrics
+//! - Process state, priority, memory limit, group and metrics
 //! - System-wide metrics
 
+use alloc::collections::BTreeMap;
 use alloc::string::String;
 
 // Re-export types from zos-axiom to maintain backwards compatibility
 pub use zos_axiom::{CapSlot, ObjectType};
+pub use zos_allocator::PoolStats;
 
 /// Process identifier
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ProcessId(pub u64);
 
 /// IPC endpoint identifier
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct EndpointId(pub u64);
 
+/// Notification identifier
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NotificationId(pub u64);
+
 /// Process state
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ProcessState {
@@ -29,6 +37,63 @@ pub enum ProcessState {
     Zombie,
 }
 
+/// Exit code recorded in `ProcessExited` when a process is killed.
+///
+/// A kill removes the process outright, while a voluntary exit leaves a
+/// zombie until it is reaped; replay uses this code to tell them apart.
+pub const KILLED_EXIT_CODE: i32 = -1;
+
+/// Memory limit a process starts with (bytes), until it declares its own
+/// with `SYS_SET_MEMORY_LIMIT`.
+pub const DEFAULT_MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// `ProcessFaulted` reason for a process that grew past its memory limit.
+pub const FAULT_OUT_OF_MEMORY: u32 = 6;
+
+/// Scheduling priority class (see `zos_ipc::priority`)
+///
+/// Ordered from most to least urgent, so `a < b` means `a` runs first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Core services the rest of the system waits on
+    SystemService,
+    /// Apps a user is looking at
+    Interactive,
+    /// Work nobody is waiting on
+    Background,
+}
+
+impl Priority {
+    /// The class a process starts in: the supervisor and Init are system
+    /// services, every other process is interactive until told otherwise.
+    pub fn initial(pid: ProcessId) -> Self {
+        if pid.0 <= zos_ipc::pid::INIT as u64 {
+            Priority::SystemService
+        } else {
+            Priority::Interactive
+        }
+    }
+
+    /// Parse a `zos_ipc::priority` class.
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            zos_ipc::priority::SYSTEM_SERVICE => Some(Priority::SystemService),
+            zos_ipc::priority::INTERACTIVE => Some(Priority::Interactive),
+            zos_ipc::priority::BACKGROUND => Some(Priority::Background),
+            _ => None,
+        }
+    }
+
+    /// The `zos_ipc::priority` class.
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Priority::SystemService => zos_ipc::priority::SYSTEM_SERVICE,
+            Priority::Interactive => zos_ipc::priority::INTERACTIVE,
+            Priority::Background => zos_ipc::priority::BACKGROUND,
+        }
+    }
+}
+
 /// Process descriptor
 pub struct Process {
     /// Process ID
@@ -37,6 +102,12 @@ pub struct Process {
     pub name: String,
     /// Current state
     pub state: ProcessState,
+    /// Scheduling priority class
+    pub priority: Priority,
+    /// Most memory the process may grow to (bytes)
+    pub memory_limit: usize,
+    /// Process group; a group's ID is its leader's PID
+    pub pgid: ProcessId,
     /// Detailed metrics for this process
     pub metrics: ProcessMetrics,
 }
@@ -60,6 +131,14 @@ pub struct ProcessMetrics {
     pub last_active_ns: u64,
     /// Process start time (nanos since boot)
     pub start_time_ns: u64,
+    /// Time spent running between syscalls (nanos)
+    pub run_time_ns: u64,
+    /// Times the process went back to work after waiting
+    pub wakeups: u64,
+    /// When the last syscall returned, unless it left the process waiting
+    pub resumed_ns: Option<u64>,
+    /// Number of the last syscall made (0 before the first)
+    pub last_syscall: u32,
 }
 
 /// Per-endpoint tracking
@@ -71,6 +150,8 @@ pub struct EndpointMetrics {
     pub total_messages: u64,
     /// Total bytes received
     pub total_bytes: u64,
+    /// Messages ever sent to this endpoint, by sender
+    pub sent_by: BTreeMap<ProcessId, u64>,
     /// High water mark (max queue depth seen)
     pub queue_high_water: usize,
 }
@@ -90,4 +171,6 @@ pub struct SystemMetrics {
     pub total_ipc_messages: u64,
     /// Uptime in nanoseconds
     pub uptime_ns: u64,
+    /// Message buffer pool counters
+    pub message_pool: PoolStats,
 }
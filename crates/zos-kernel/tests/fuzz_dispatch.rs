@@ -0,0 +1,713 @@
+//! Syscall dispatch fuzzer
+//!
+//! Feeds [`System::process_syscall`] - the dispatch layer every process
+//! goes through - with generated syscalls: known and unknown syscall
+//! numbers, arguments drawn from patterns that sit on and around the
+//! interesting values (valid slots, slots one past the end, live and dead
+//! PIDs, zero, `u32::MAX`, sign bits, packed length/count words), payloads
+//! from empty to oversized, and callers that are live, dead or were never
+//! registered.
+//!
+//! Like the soak harness, a run is a sequence of seeded *epochs*. After
+//! every call the fuzzer checks:
+//!
+//! - **No panics**: the call returned
+//! - **Recomputable state hash**: the incremental state digest matches one
+//!   rebuilt from the live state, and (every [`REPLAY_INTERVAL`] calls and
+//!   at the end) replaying the CommitLog into a fresh system reproduces it
+//! - **No capability without a grant event**: every capability in every
+//!   CSpace was put there by a `CapInserted` or `CapGranted` commit for
+//!   that slot, and every capability those commits put in place is still
+//!   there unless a later commit removed it
+//!
+//! A failure is re-run replaying after every call, and reports the seed
+//! and call count that reproduce it.
+//!
+//! ```text
+//! # Short run (part of the normal test suite)
+//! cargo test -p zos-kernel --test fuzz_dispatch
+//!
+//! # Continuous run, default 1 hour (override with ZOS_FUZZ_SECS)
+//! ZOS_FUZZ_SECS=3600 cargo test -p zos-kernel --release --test fuzz_dispatch fuzz_long -- --ignored --nocapture
+//!
+//! # Reproduce a reported failure
+//! ZOS_FUZZ_SEED=0x... ZOS_FUZZ_CALLS=1234 cargo test -p zos-kernel --test fuzz_dispatch fuzz_reproduce -- --ignored
+//! ```
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+use zos_axiom::CommitType;
+use zos_hal::{HalError, NumericProcessHandle, HAL};
+use zos_ipc::syscall::*;
+use zos_kernel::{replay_and_verify, CapSlot, ProcessId, Replayable, System, MAX_MESSAGE_SIZE};
+
+/// Syscalls per epoch. Keeps the CommitLog well below its retention limit
+/// so the whole log can be replayed.
+const CALLS_PER_EPOCH: u32 = 2048;
+
+/// Calls between full replays of the CommitLog.
+const REPLAY_INTERVAL: u32 = 256;
+
+/// Live processes the fuzzer keeps around to make calls and be targeted.
+const MIN_PROCESSES: usize = 4;
+
+/// Upper bound on live processes.
+const MAX_PROCESSES: usize = 16;
+
+/// Default length of the continuous run.
+const DEFAULT_FUZZ_SECS: u64 = 60 * 60;
+
+/// Every syscall number the ABI defines.
+const KNOWN_SYSCALLS: &[u32] = &[
+    SYS_DEBUG,
+    SYS_YIELD,
+    SYS_EXIT,
+    SYS_TIME,
+    SYS_RANDOM,
+    SYS_WALLCLOCK,
+    SYS_CONSOLE_WRITE,
+    SYS_TIMER_CREATE,
+    SYS_TIMER_CANCEL,
+    SYS_CREATE_ENDPOINT,
+    SYS_DELETE_ENDPOINT,
+    SYS_KILL,
+    SYS_REGISTER_PROCESS,
+    SYS_CREATE_ENDPOINT_FOR,
+    SYS_LOAD_BINARY,
+    SYS_SPAWN_PROCESS,
+    SYS_BIND_NAME,
+    SYS_BROADCAST_CREATE,
+    SYS_SET_PRIORITY,
+    SYS_SET_MEMORY_LIMIT,
+    SYS_PROC_STATE,
+    SYS_PROC_READ_MEM,
+    SYS_SETPGID,
+    SYS_CAP_GRANT,
+    SYS_CAP_REVOKE,
+    SYS_CAP_DELETE,
+    SYS_CAP_INSPECT,
+    SYS_CAP_DERIVE,
+    SYS_CAP_LIST,
+    SYS_SEND,
+    SYS_RECV,
+    SYS_CALL,
+    SYS_REPLY,
+    SYS_SEND_CAP,
+    SYS_SEND_NAMED,
+    SYS_SET_DEADLINE,
+    SYS_GET_DEADLINE,
+    SYS_SET_CORRELATION,
+    SYS_GET_CORRELATION,
+    SYS_CALL_WAIT,
+    SYS_NOTIFY_CREATE,
+    SYS_SIGNAL,
+    SYS_WAIT,
+    SYS_SUBSCRIBE,
+    SYS_PUBLISH,
+    SYS_PS,
+    SYS_SHM_CREATE,
+    SYS_SHM_MAP,
+    SYS_SHM_UNMAP,
+    SYS_SHM_FLUSH,
+    SYS_SHM_FETCH,
+    SYS_STORAGE_READ,
+    SYS_STORAGE_WRITE,
+    SYS_STORAGE_DELETE,
+    SYS_STORAGE_LIST,
+    SYS_STORAGE_EXISTS,
+    SYS_STORAGE_BATCH_WRITE,
+    SYS_STORAGE_CANCEL,
+    SYS_KEYSTORE_READ,
+    SYS_KEYSTORE_WRITE,
+    SYS_KEYSTORE_DELETE,
+    SYS_KEYSTORE_LIST,
+    SYS_KEYSTORE_EXISTS,
+    SYS_NETWORK_FETCH,
+];
+
+/// Names a payload may spell, so name lookups sometimes hit.
+const NAMES: &[&str] = &["init", "vfs", "permission", "fuzz", ""];
+
+// ============================================================================
+// Fuzz HAL
+// ============================================================================
+
+/// Native HAL for the fuzzer: a manually advanced clock and nothing else.
+/// Storage, keystore and network requests fail with the trait defaults.
+#[derive(Default)]
+struct FuzzHal {
+    time: AtomicU64,
+}
+
+impl FuzzHal {
+    fn advance(&self, nanos: u64) {
+        self.time.fetch_add(nanos, Ordering::SeqCst);
+    }
+}
+
+impl HAL for FuzzHal {
+    type ProcessHandle = NumericProcessHandle;
+
+    fn spawn_process(&self, _name: &str, _binary: &[u8]) -> Result<Self::ProcessHandle, HalError> {
+        Err(HalError::NotSupported)
+    }
+
+    fn kill_process(&self, _handle: &Self::ProcessHandle) -> Result<(), HalError> {
+        Err(HalError::NotSupported)
+    }
+
+    fn send_to_process(&self, _handle: &Self::ProcessHandle, _msg: &[u8]) -> Result<(), HalError> {
+        Ok(())
+    }
+
+    fn is_process_alive(&self, _handle: &Self::ProcessHandle) -> bool {
+        true
+    }
+
+    fn get_process_memory_size(&self, _handle: &Self::ProcessHandle) -> Result<usize, HalError> {
+        Ok(65536)
+    }
+
+    fn allocate(&self, _size: usize, _align: usize) -> Result<*mut u8, HalError> {
+        Err(HalError::NotSupported)
+    }
+
+    unsafe fn deallocate(&self, _ptr: *mut u8, _size: usize, _align: usize) {}
+
+    fn now_nanos(&self) -> u64 {
+        self.time.load(Ordering::SeqCst)
+    }
+
+    fn wallclock_ms(&self) -> u64 {
+        1737504000000 + self.now_nanos() / 1_000_000
+    }
+
+    fn random_bytes(&self, buf: &mut [u8]) -> Result<(), HalError> {
+        buf.fill(0);
+        Ok(())
+    }
+
+    fn debug_write(&self, _msg: &str) {}
+
+    fn poll_messages(&self) -> Vec<(Self::ProcessHandle, Vec<u8>)> {
+        Vec::new()
+    }
+}
+
+// ============================================================================
+// Input generation
+// ============================================================================
+
+/// SplitMix64, so a seed fully determines an epoch.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> Option<T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(items[self.below(items.len() as u64) as usize])
+        }
+    }
+}
+
+/// One generated syscall.
+#[derive(Clone, Debug)]
+struct Call {
+    caller: ProcessId,
+    num: u32,
+    args: [u32; 4],
+    data: Vec<u8>,
+}
+
+impl core::fmt::Display for Call {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "PID {} syscall {:#04x} args {:#x?} data {} bytes",
+            self.caller.0,
+            self.num,
+            self.args,
+            self.data.len()
+        )
+    }
+}
+
+/// A fresh system, the fuzzer's RNG, and the capabilities the CommitLog
+/// says each CSpace holds.
+struct FuzzBox {
+    system: System<FuzzHal>,
+    rng: Rng,
+    /// Capability ID in each (PID, slot), from the commits seen so far
+    granted: BTreeMap<(u64, CapSlot), u64>,
+    /// Sequence number of the last commit folded into `granted`
+    seen_seq: u64,
+    /// Processes that have exited, to call as and to target
+    dead: Vec<ProcessId>,
+    /// The last call made, for failure reports
+    last_call: Option<Call>,
+}
+
+impl FuzzBox {
+    fn boot(seed: u64) -> Self {
+        let mut fbox = Self {
+            system: System::new(FuzzHal::default()),
+            rng: Rng(seed),
+            granted: BTreeMap::new(),
+            seen_seq: 0,
+            dead: Vec::new(),
+            last_call: None,
+        };
+        fbox.system.register_process("init");
+        fbox.populate();
+        fbox.fold_commits();
+        fbox
+    }
+
+    fn live_pids(&self) -> Vec<ProcessId> {
+        self.system
+            .list_processes()
+            .into_iter()
+            .map(|(pid, _)| pid)
+            .collect()
+    }
+
+    /// Slots `pid` holds capabilities in.
+    fn slots(&self, pid: ProcessId) -> Vec<CapSlot> {
+        self.system
+            .get_cap_space(pid)
+            .map(|cspace| cspace.slots.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Register processes, each with an endpoint, until there are enough
+    /// to call as.
+    fn populate(&mut self) {
+        while self.system.list_processes().len() < MIN_PROCESSES {
+            let name = format!("fuzz-{}", self.rng.below(1000));
+            let pid = self.system.register_process(&name);
+            let _ = self.system.create_endpoint(pid);
+        }
+    }
+
+    /// Generate and make one call.
+    fn step(&mut self) -> Result<(), String> {
+        self.system.hal().advance(self.rng.below(2_000_000));
+        let call = self.generate();
+        self.last_call = Some(call.clone());
+        let before = self.live_pids();
+
+        let system = &mut self.system;
+        catch_unwind(AssertUnwindSafe(|| {
+            system.process_syscall(call.caller, call.num, call.args, &call.data);
+            system.fire_timers();
+        }))
+        .map_err(|panic| {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| String::from(*s))
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            format!("dispatch panicked: {}", message)
+        })?;
+
+        let live = self.live_pids();
+        self.dead
+            .extend(before.into_iter().filter(|pid| !live.contains(pid)));
+        if live.len() < MAX_PROCESSES {
+            self.populate();
+        }
+        Ok(())
+    }
+
+    fn generate(&mut self) -> Call {
+        let live = self.live_pids();
+        let caller = match self.rng.below(20) {
+            0 => self.rng.pick(&self.dead).unwrap_or(ProcessId(0)),
+            1 => ProcessId(self.rng.below(u32::MAX as u64)),
+            2..=4 => ProcessId(1),
+            _ => self.rng.pick(&live).unwrap_or(ProcessId(1)),
+        };
+        let num = match self.rng.below(10) {
+            0 => self.rng.below(0x100) as u32,
+            1 => self.rng.next() as u32,
+            _ => self.rng.pick(KNOWN_SYSCALLS).unwrap_or(SYS_DEBUG),
+        };
+        let data = self.generate_data(caller);
+        let args = [
+            self.generate_arg(caller, &live, data.len()),
+            self.generate_arg(caller, &live, data.len()),
+            self.generate_arg(caller, &live, data.len()),
+            self.generate_arg(caller, &live, data.len()),
+        ];
+        Call {
+            caller,
+            num,
+            args,
+            data,
+        }
+    }
+
+    fn generate_arg(&mut self, caller: ProcessId, live: &[ProcessId], data_len: usize) -> u32 {
+        let slots = self.slots(caller);
+        match self.rng.below(100) {
+            0..=14 => 0,
+            15..=34 => self.rng.pick(&slots).unwrap_or(0),
+            // One past the highest slot, or a slot of some other process
+            35..=39 => slots.iter().max().map_or(0, |s| s.wrapping_add(1)),
+            40..=44 => {
+                let other = self.rng.pick(live).unwrap_or(caller);
+                self.rng.pick(&self.slots(other)).unwrap_or(1)
+            }
+            45..=54 => self.rng.pick(live).map_or(1, |pid| pid.0 as u32),
+            55..=59 => self.rng.pick(&self.dead).map_or(2, |pid| pid.0 as u32),
+            60..=69 => self.rng.below(16) as u32,
+            70..=74 => u32::MAX,
+            75..=79 => self
+                .rng
+                .pick(&[0x8000_0000, 0x7FFF_FFFF, 0xFFFF, 0x1_0000, u32::MAX - 1])
+                .unwrap_or(0),
+            // Data length with a capability count in the high half
+            80..=87 => (data_len as u32 & 0xFFFF) | ((self.rng.below(12) as u32) << 16),
+            _ => self.rng.next() as u32,
+        }
+    }
+
+    fn generate_data(&mut self, caller: ProcessId) -> Vec<u8> {
+        match self.rng.below(10) {
+            0..=3 => Vec::new(),
+            4..=5 => {
+                let len = self.rng.below(64) as usize;
+                (0..len).map(|_| self.rng.next() as u8).collect()
+            }
+            6 => {
+                let name = self.rng.pick(NAMES).unwrap_or("");
+                name.as_bytes().to_vec()
+            }
+            // Name-prefixed payload, as `SYS_SEND_NAMED` and spawn expect
+            7 => {
+                let name = self.rng.pick(NAMES).unwrap_or("");
+                let mut data = Vec::new();
+                data.push(name.len() as u8);
+                data.extend_from_slice(&(name.len() as u32).to_le_bytes()[1..]);
+                data.extend_from_slice(name.as_bytes());
+                data.extend((0..self.rng.below(16)).map(|_| self.rng.next() as u8));
+                data
+            }
+            // Message data followed by capability slots, as `SYS_SEND_CAP`
+            // expects
+            8 => {
+                let mut data: Vec<u8> = (0..self.rng.below(16)).map(|i| i as u8).collect();
+                let slots = self.slots(caller);
+                for _ in 0..self.rng.below(4) {
+                    let slot = self.rng.pick(&slots).unwrap_or(u32::MAX);
+                    data.extend_from_slice(&slot.to_le_bytes());
+                }
+                data
+            }
+            _ => alloc::vec![0xA5; MAX_MESSAGE_SIZE + self.rng.below(4) as usize],
+        }
+    }
+
+    // ========================================================================
+    // Invariants
+    // ========================================================================
+
+    /// Fold the commits appended since the last check into `granted`.
+    fn fold_commits(&mut self) {
+        let new: Vec<CommitType> = self
+            .system
+            .commitlog()
+            .commits()
+            .iter()
+            .filter(|c| c.seq > self.seen_seq)
+            .map(|c| c.commit_type.clone())
+            .collect();
+        self.seen_seq = self.last_seq();
+        for commit_type in new {
+            self.fold(commit_type);
+        }
+    }
+
+    fn last_seq(&self) -> u64 {
+        self.system
+            .commitlog()
+            .commits()
+            .last()
+            .map_or(0, |c| c.seq)
+    }
+
+    fn fold(&mut self, commit_type: CommitType) {
+        match commit_type {
+            CommitType::CapInserted {
+                pid, slot, cap_id, ..
+            } => {
+                self.granted.insert((pid, slot), cap_id);
+            }
+            CommitType::CapGranted {
+                to_pid,
+                to_slot,
+                new_cap_id,
+                ..
+            } => {
+                self.granted.insert((to_pid, to_slot), new_cap_id);
+            }
+            CommitType::CapRemoved { pid, slot } => {
+                self.granted.remove(&(pid, slot));
+            }
+            CommitType::ProcessExited { pid, .. } | CommitType::ProcessFaulted { pid, .. } => {
+                self.granted.retain(|&(owner, _), _| owner != pid);
+            }
+            CommitType::Batch { events } => {
+                for event in events {
+                    self.fold(event);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_invariants(&mut self, replay: bool) -> Result<(), String> {
+        self.check_capabilities()?;
+        if !self.system.verify_state_digest() {
+            return Err(String::from("incremental state digest drifted from state"));
+        }
+        if replay {
+            self.check_replay()?;
+        }
+        Ok(())
+    }
+
+    fn check_capabilities(&mut self) -> Result<(), String> {
+        self.fold_commits();
+        let mut held = 0;
+        for pid in self.live_pids() {
+            let Some(cspace) = self.system.get_cap_space(pid) else {
+                continue;
+            };
+            for (&slot, cap) in &cspace.slots {
+                held += 1;
+                match self.granted.get(&(pid.0, slot)) {
+                    Some(&id) if id == cap.id => {}
+                    Some(&id) => {
+                        return Err(format!(
+                            "PID {} slot {} holds cap {}, but the last grant there was cap {}",
+                            pid.0, slot, cap.id, id
+                        ))
+                    }
+                    None => {
+                        return Err(format!(
+                            "PID {} slot {} holds cap {} ({:?} {}) with no grant event",
+                            pid.0, slot, cap.id, cap.object_type, cap.object_id
+                        ))
+                    }
+                }
+            }
+        }
+        if held != self.granted.len() {
+            let (&(pid, slot), id) = self
+                .granted
+                .iter()
+                .find(|(&(pid, slot), _)| {
+                    self.system
+                        .get_cap_space(ProcessId(pid))
+                        .is_none_or(|cspace| !cspace.slots.contains_key(&slot))
+                })
+                .expect("a granted capability is missing");
+            return Err(format!(
+                "cap {} granted to PID {} slot {} vanished without a removal event",
+                id, pid, slot
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_replay(&self) -> Result<(), String> {
+        let log = self.system.commitlog();
+        if !log.verify_integrity() {
+            return Err(String::from("CommitLog hash chain is broken"));
+        }
+        let mut replayed = System::<FuzzHal>::new_for_replay();
+        replay_and_verify(&mut replayed, log.commits(), self.system.state_hash())
+            .map_err(|e| format!("replay of {} commits diverged: {:?}", log.len(), e))
+    }
+}
+
+// ============================================================================
+// Runner
+// ============================================================================
+
+/// An invariant violation found by an epoch.
+#[derive(Debug)]
+struct Violation {
+    /// Epoch seed
+    seed: u64,
+    /// Calls made when the violation was detected
+    calls: u32,
+    /// Call that led to it
+    last_call: String,
+    /// Which invariant failed, and how
+    reason: String,
+}
+
+impl core::fmt::Display for Violation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "fuzz invariant violated after {} calls (seed {:#018x}): {}\n  last call: {}\n  reproduce: ZOS_FUZZ_SEED={:#018x} ZOS_FUZZ_CALLS={} cargo test -p zos-kernel --test fuzz_dispatch fuzz_reproduce -- --ignored",
+            self.calls, self.seed, self.reason, self.last_call, self.seed, self.calls
+        )
+    }
+}
+
+/// Make `calls` calls from `seed`, checking after each and replaying every
+/// `replay_interval` calls and at the end.
+fn run_epoch(seed: u64, calls: u32, replay_interval: u32) -> Result<(), Violation> {
+    let mut fbox = FuzzBox::boot(seed);
+    for i in 1..=calls {
+        let replay = i % replay_interval == 0 || i == calls;
+        fbox.step()
+            .and_then(|()| fbox.check_invariants(replay))
+            .map_err(|reason| Violation {
+                seed,
+                calls: i,
+                last_call: fbox
+                    .last_call
+                    .as_ref()
+                    .map_or_else(String::new, |c| c.to_string()),
+                reason,
+            })?;
+    }
+    Ok(())
+}
+
+/// Run an epoch; on failure, re-run it replaying after every call so the
+/// report names the call that broke replay.
+fn run_epoch_minimized(seed: u64) -> Result<(), Violation> {
+    match run_epoch(seed, CALLS_PER_EPOCH, REPLAY_INTERVAL) {
+        Ok(()) => Ok(()),
+        Err(found) => Err(run_epoch(seed, found.calls, 1).err().unwrap_or(found)),
+    }
+}
+
+/// Seed of the `index`th epoch of a run.
+fn epoch_seed(run_seed: u64, index: u64) -> u64 {
+    Rng(run_seed ^ index.wrapping_mul(0xA24B_AED4_963E_E407)).next()
+}
+
+/// Run epochs until `budget` elapses (at least `min_epochs`), returning the
+/// number of epochs run.
+fn fuzz(run_seed: u64, budget: Duration, min_epochs: u64) -> u64 {
+    let start = Instant::now();
+    let mut epochs = 0;
+    while epochs < min_epochs || start.elapsed() < budget {
+        let seed = epoch_seed(run_seed, epochs);
+        if let Err(violation) = run_epoch_minimized(seed) {
+            panic!("{}", violation);
+        }
+        epochs += 1;
+    }
+    epochs
+}
+
+fn env_u64(name: &str) -> Option<u64> {
+    let value = std::env::var(name).ok()?;
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn fuzz_short() {
+    let epochs = fuzz(0xF022, Duration::ZERO, 4);
+    assert_eq!(epochs, 4);
+}
+
+#[test]
+fn fuzz_epoch_is_deterministic() {
+    let mut a = FuzzBox::boot(42);
+    let mut b = FuzzBox::boot(42);
+    for _ in 0..500 {
+        a.step().unwrap();
+        b.step().unwrap();
+        assert_eq!(
+            a.last_call.as_ref().map(|c| c.to_string()),
+            b.last_call.as_ref().map(|c| c.to_string())
+        );
+    }
+    assert_eq!(a.system.state_hash(), b.system.state_hash());
+}
+
+#[test]
+fn fuzz_detects_unlogged_capability() {
+    let mut fbox = FuzzBox::boot(7);
+    let pid = fbox.live_pids()[1];
+    // Insert a capability without a commit
+    let (_, slot) = fbox.system.create_endpoint(pid).unwrap();
+    let cap = fbox
+        .system
+        .get_cap_space(pid)
+        .unwrap()
+        .get(slot)
+        .unwrap()
+        .clone();
+    fbox.seen_seq = fbox.last_seq();
+    let err = fbox.check_capabilities().unwrap_err();
+    assert!(err.contains(&format!("holds cap {}", cap.id)), "{}", err);
+}
+
+/// Continuous fuzzing. Runs for `ZOS_FUZZ_SECS` (default 1 hour) from
+/// `ZOS_FUZZ_SEED` (default: wall clock).
+#[test]
+#[ignore]
+fn fuzz_long() {
+    let secs = env_u64("ZOS_FUZZ_SECS").unwrap_or(DEFAULT_FUZZ_SECS);
+    let run_seed = env_u64("ZOS_FUZZ_SEED").unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+    });
+    println!("fuzz: run seed {:#018x}, {}s budget", run_seed, secs);
+    let epochs = fuzz(run_seed, Duration::from_secs(secs), 1);
+    println!(
+        "fuzz: {} epochs ({} calls) without violations",
+        epochs,
+        epochs * CALLS_PER_EPOCH as u64
+    );
+}
+
+/// Re-run a single epoch reported by a failing fuzz run.
+#[test]
+#[ignore]
+fn fuzz_reproduce() {
+    let seed = env_u64("ZOS_FUZZ_SEED").expect("set ZOS_FUZZ_SEED to the reported seed");
+    let calls = env_u64("ZOS_FUZZ_CALLS").map_or(CALLS_PER_EPOCH, |n| n as u32);
+    if let Err(violation) = run_epoch(seed, calls, 1) {
+        panic!("{}", violation);
+    }
+}
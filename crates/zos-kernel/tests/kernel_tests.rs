@@ -12,8 +12,8 @@ use core::cell::RefCell;
 use core::sync::atomic::{AtomicU64, Ordering};
 use zos_hal::{HalError, NumericProcessHandle, HAL};
 use zos_kernel::{
-    axiom_check, AxiomError, Capability, CapabilitySpace, ObjectType, Permissions, ProcessId,
-    ProcessState, System,
+    axiom_check, AxiomError, CapSlot, Capability, CapabilitySpace, KernelError, ObjectType,
+    Permissions, ProcessId, ProcessState, System,
 };
 
 // ============================================================================
@@ -147,6 +147,11 @@ impl HAL for MockHal {
             .ok_or(HalError::ProcessNotFound)
     }
 
+    fn read_process_memory(&self, _pid: u64, addr: u32, len: u32) -> Result<Vec<u8>, HalError> {
+        // Each byte is the low byte of its address
+        Ok((addr..addr + len).map(|a| a as u8).collect())
+    }
+
     fn allocate(&self, size: usize, _align: usize) -> Result<*mut u8, HalError> {
         let layout =
             core::alloc::Layout::from_size_align(size, 8).map_err(|_| HalError::InvalidArgument)?;
@@ -331,6 +336,54 @@ fn test_ipc_send_receive() {
     assert_eq!(msg.data, b"hello world");
 }
 
+#[test]
+fn test_fault_injection_fails_sends_and_records_seed() {
+    use zos_kernel::chaos::{FAULT_IPC, TRANSIENT_SEND_ERROR};
+    use zos_kernel::{ChaosConfig, CommitType, SYS_SEND};
+
+    let hal = MockHal::new();
+    let mut kernel = System::new(hal);
+
+    let sender_pid = kernel.register_process("sender");
+    let receiver_pid = kernel.register_process("receiver");
+    let (_, receiver_slot) = kernel.create_endpoint(receiver_pid).unwrap();
+    let sender_slot = kernel
+        .grant_capability(
+            receiver_pid,
+            receiver_slot,
+            sender_pid,
+            Permissions {
+                read: false,
+                write: true,
+                grant: false,
+            },
+        )
+        .unwrap();
+
+    let mut config = ChaosConfig::with_faults(FAULT_IPC);
+    config.send_fail_pct = 100;
+    kernel.enable_fault_injection(0xC0FFEE, config);
+
+    let seeded = kernel.commitlog().commits().iter().any(|c| {
+        matches!(
+            c.commit_type,
+            CommitType::FaultInjectionSeeded { seed: 0xC0FFEE, faults } if faults == FAULT_IPC
+        )
+    });
+    assert!(seeded, "seed must be recorded in the CommitLog");
+
+    let (result, _, _) =
+        kernel.process_syscall(sender_pid, SYS_SEND, [sender_slot, 42, 2, 0], b"hi");
+    assert_eq!(result, TRANSIENT_SEND_ERROR);
+    let ep = kernel.get_endpoint(zos_kernel::EndpointId(1)).unwrap();
+    assert!(ep.pending_messages.is_empty());
+
+    kernel.disable_fault_injection();
+    let (result, _, _) =
+        kernel.process_syscall(sender_pid, SYS_SEND, [sender_slot, 42, 2, 0], b"hi");
+    assert_eq!(result, 0);
+}
+
 #[test]
 fn test_axiom_check_valid_capability() {
     let mut cspace = CapabilitySpace::new();
@@ -476,6 +529,8 @@ fn test_sys_create_endpoint_for_init_only() {
         endpoints[0].owner, target_pid,
         "Endpoint should be owned by target"
     );
+    // Init's capability is logged like the owner's
+    assert!(kernel.verify_state_digest());
 
     // Other processes should NOT be able to create endpoints for others
     let (result2, _rich2, _data2) = kernel.process_syscall(
@@ -759,6 +814,411 @@ fn test_derive_cannot_escalate_permissions() {
     }
 }
 
+// ============================================================================
+// Named Capability Tests - SYS_BIND_NAME, SYS_SEND_NAMED
+// ============================================================================
+
+/// Spawn a service the way Init does: slot 0 output, slot 1 input endpoint.
+fn spawn_named_service(kernel: &mut System<MockHal>, name: &str) -> ProcessId {
+    let pid = kernel.register_process(name);
+    kernel.create_endpoint(pid).expect("output endpoint");
+    kernel.create_endpoint(pid).expect("input endpoint");
+    pid
+}
+
+/// Build a SYS_SEND_NAMED payload.
+fn named_payload(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut payload = alloc::vec![name.len() as u8];
+    payload.extend_from_slice(name.as_bytes());
+    payload.extend_from_slice(data);
+    payload
+}
+
+#[test]
+fn test_bind_name_init_only() {
+    use zos_ipc::syscall::SYS_BIND_NAME;
+
+    let mut kernel = System::new(MockHal::new());
+    let init = kernel.register_process_with_pid(ProcessId(1), "init");
+    let vfs = spawn_named_service(&mut kernel, "vfs");
+    let other = kernel.register_process("other");
+
+    let (result, _, _) =
+        kernel.process_syscall(other, SYS_BIND_NAME, [vfs.0 as u32, 0, 0, 0], b"vfs");
+    assert_eq!(result, -4, "Non-Init processes should not bind names");
+
+    let (result, _, _) =
+        kernel.process_syscall(init, SYS_BIND_NAME, [vfs.0 as u32, 0, 0, 0], b"vfs");
+    assert!(result > 0, "Init should bind names, got {}", result);
+
+    // A process without an input endpoint cannot be bound
+    let (result, _, _) =
+        kernel.process_syscall(init, SYS_BIND_NAME, [other.0 as u32, 0, 0, 0], b"other");
+    assert_eq!(result, -5);
+}
+
+#[test]
+fn test_send_named_resolves_once() {
+    use zos_ipc::syscall::{SYS_BIND_NAME, SYS_SEND_NAMED};
+
+    let mut kernel = System::new(MockHal::new());
+    let init = kernel.register_process_with_pid(ProcessId(1), "init");
+    let vfs = spawn_named_service(&mut kernel, "vfs");
+    let client = kernel.register_process("client");
+
+    // Unbound names fail without touching the CSpace
+    let payload = named_payload("vfs", b"hi");
+    let (result, _, _) = kernel.process_syscall(client, SYS_SEND_NAMED, [7, 0, 0, 0], &payload);
+    assert!(result < 0, "Unbound name should fail");
+    assert_eq!(kernel.get_cap_space(client).map_or(0, |cs| cs.len()), 0);
+
+    kernel.process_syscall(init, SYS_BIND_NAME, [vfs.0 as u32, 0, 0, 0], b"vfs");
+    for _ in 0..2 {
+        let (result, _, _) =
+            kernel.process_syscall(client, SYS_SEND_NAMED, [7, 0, 0, 0], &payload);
+        assert_eq!(result, 0);
+    }
+
+    // One send-only capability, reused for the second send
+    let cspace = kernel.get_cap_space(client).expect("client cspace");
+    assert_eq!(cspace.len(), 1);
+    let msg = kernel
+        .ipc_receive(vfs, 1)
+        .expect("receive should succeed")
+        .expect("message should be present");
+    assert_eq!(msg.from, client);
+    assert_eq!(msg.tag, 7);
+    assert_eq!(msg.data, b"hi");
+}
+
+#[test]
+fn test_service_graph_shows_authority_and_traffic() {
+    use zos_ipc::syscall::{SYS_BIND_NAME, SYS_SEND_NAMED};
+
+    let mut kernel = System::new(MockHal::new());
+    let init = kernel.register_process_with_pid(ProcessId(1), "init");
+    let vfs = spawn_named_service(&mut kernel, "vfs");
+    let client = kernel.register_process("client");
+    let idle = kernel.register_process("idle");
+    kernel.process_syscall(init, SYS_BIND_NAME, [vfs.0 as u32, 0, 0, 0], b"vfs");
+
+    let payload = named_payload("vfs", b"hi");
+    for _ in 0..2 {
+        kernel.process_syscall(client, SYS_SEND_NAMED, [7, 0, 0, 0], &payload);
+    }
+    kernel
+        .grant_capability(vfs, 1, idle, Permissions::write_only())
+        .expect("grant");
+
+    let graph = kernel.service_graph();
+    assert_eq!(graph.nodes.len(), 4);
+    let node = graph.nodes.iter().find(|n| n.pid == vfs).unwrap();
+    assert_eq!(node.services, ["vfs"]);
+    assert_eq!(node.endpoints.len(), 2);
+
+    let edge = graph.edge(client, vfs).expect("client reaches vfs");
+    assert_eq!((edge.capabilities, edge.messages), (1, 2));
+    let edge = graph.edge(idle, vfs).expect("idle may reach vfs");
+    assert_eq!((edge.capabilities, edge.messages), (1, 0));
+    // A service's hold on its own endpoints is not an edge
+    assert_eq!(graph.edges.len(), 2);
+
+    // Edges, and the traffic counted for them, go with the process
+    kernel.kill_process(client);
+    let graph = kernel.service_graph();
+    assert!(graph.edge(client, vfs).is_none());
+    let input = graph.nodes.iter().find(|n| n.pid == vfs).unwrap().endpoints[1];
+    let detail = kernel.get_endpoint_detail(input).unwrap();
+    assert_eq!(detail.metrics.total_messages, 2);
+    assert!(detail.metrics.sent_by.is_empty());
+}
+
+#[test]
+fn test_send_named_follows_restart() {
+    use zos_ipc::syscall::{SYS_BIND_NAME, SYS_SEND_NAMED};
+
+    let mut kernel = System::new(MockHal::new());
+    let init = kernel.register_process_with_pid(ProcessId(1), "init");
+    let old = spawn_named_service(&mut kernel, "vfs");
+    let client = kernel.register_process("client");
+    let payload = named_payload("vfs", b"hi");
+
+    kernel.process_syscall(init, SYS_BIND_NAME, [old.0 as u32, 0, 0, 0], b"vfs");
+    kernel.process_syscall(client, SYS_SEND_NAMED, [7, 0, 0, 0], &payload);
+
+    // The service dies; sends fail until it is bound again
+    kernel.kill_process(old);
+    let (result, _, _) = kernel.process_syscall(client, SYS_SEND_NAMED, [7, 0, 0, 0], &payload);
+    assert!(result < 0, "Send to a dead service should fail");
+
+    let new = spawn_named_service(&mut kernel, "vfs");
+    kernel.process_syscall(init, SYS_BIND_NAME, [new.0 as u32, 0, 0, 0], b"vfs");
+    let (result, _, _) = kernel.process_syscall(client, SYS_SEND_NAMED, [7, 0, 0, 0], &payload);
+    assert_eq!(result, 0);
+
+    // The stale capability was replaced, not accumulated
+    assert_eq!(kernel.get_cap_space(client).map_or(0, |cs| cs.len()), 1);
+    let msg = kernel
+        .ipc_receive(new, 1)
+        .expect("receive should succeed")
+        .expect("message should be present");
+    assert_eq!(msg.from, client);
+}
+
+#[test]
+fn test_deadline_propagates_and_expires() {
+    use zos_ipc::syscall::{SYS_GET_DEADLINE, SYS_RECV, SYS_SEND, SYS_SET_DEADLINE};
+
+    let mut kernel = System::new(MockHal::new());
+    let client = kernel.register_process("client");
+    let service = kernel.register_process("service");
+    let (_, service_slot) = kernel.create_endpoint(service).unwrap();
+    let client_slot = kernel
+        .grant_capability(service, service_slot, client, Permissions::write_only())
+        .unwrap();
+
+    // Sent with a deadline far in the future; the receiver takes it on
+    let deadline: u64 = 7 << 32 | 5;
+    kernel.process_syscall(client, SYS_SET_DEADLINE, [5, 7, 0, 0], &[]);
+    kernel.process_syscall(client, SYS_SEND, [client_slot, 1, 0, 0], b"a");
+    let (result, _, _) = kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    assert_eq!(result, 1);
+    let (low, _, _) = kernel.process_syscall(service, SYS_GET_DEADLINE, [0, 0, 0, 0], &[]);
+    let (high, _, _) = kernel.process_syscall(service, SYS_GET_DEADLINE, [1, 0, 0, 0], &[]);
+    assert_eq!((high as u64) << 32 | low as u64, deadline);
+
+    // A message whose deadline passes while queued is dropped unseen
+    kernel.process_syscall(client, SYS_SET_DEADLINE, [500, 0, 0, 0], &[]);
+    kernel.process_syscall(client, SYS_SEND, [client_slot, 2, 0, 0], b"late");
+    kernel.hal().time.store(1_000, Ordering::SeqCst);
+    kernel.process_syscall(client, SYS_SET_DEADLINE, [0, 0, 0, 0], &[]);
+    kernel.process_syscall(client, SYS_SEND, [client_slot, 3, 0, 0], b"b");
+    let msg = kernel
+        .ipc_receive(service, service_slot)
+        .expect("receive should succeed")
+        .expect("message should be present");
+    assert_eq!(msg.tag, 3);
+    assert_eq!(msg.deadline, None);
+    let (low, _, _) = kernel.process_syscall(service, SYS_GET_DEADLINE, [0, 0, 0, 0], &[]);
+    assert_eq!(low, 0, "A message without a deadline clears the receiver's");
+}
+
+#[test]
+fn test_correlation_id_is_echoed() {
+    use zos_ipc::frame::Frame;
+    use zos_ipc::syscall::{SYS_GET_CORRELATION, SYS_RECV, SYS_SEND, SYS_SET_CORRELATION};
+
+    let mut kernel = System::new(MockHal::new());
+    let client = kernel.register_process("client");
+    let service = kernel.register_process("service");
+    let (_, service_slot) = kernel.create_endpoint(service).unwrap();
+    let (_, reply_slot) = kernel.create_endpoint(client).unwrap();
+    let client_slot = kernel
+        .grant_capability(service, service_slot, client, Permissions::write_only())
+        .unwrap();
+    let service_reply_slot = kernel
+        .grant_capability(client, reply_slot, service, Permissions::write_only())
+        .unwrap();
+
+    // The request carries the client's ID, and the receiver takes it on
+    kernel.process_syscall(client, SYS_SET_CORRELATION, [42, 0, 0, 0], &[]);
+    kernel.process_syscall(client, SYS_SEND, [client_slot, 1, 0, 0], b"ping");
+    let (_, _, data) = kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    let request = Frame::decode(&data).unwrap();
+    assert_eq!(request.correlation_id, Some(42));
+    assert_eq!(request.data, b"ping");
+    let (current, _, _) = kernel.process_syscall(service, SYS_GET_CORRELATION, [0; 4], &[]);
+    assert_eq!(current, 42);
+
+    // A reply sent while handling it echoes the ID unprompted
+    kernel.process_syscall(service, SYS_SEND, [service_reply_slot, 2, 0, 0], b"pong");
+    let (_, _, data) = kernel.process_syscall(client, SYS_RECV, [reply_slot, 0, 0, 0], &[]);
+    assert_eq!(Frame::decode(&data).unwrap().correlation_id, Some(42));
+
+    // Clearing it sends messages without one
+    kernel.process_syscall(client, SYS_SET_CORRELATION, [0, 0, 0, 0], &[]);
+    kernel.process_syscall(client, SYS_SEND, [client_slot, 3, 0, 0], b"");
+    let (_, _, data) = kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    assert_eq!(Frame::decode(&data).unwrap().correlation_id, None);
+    let (current, _, _) = kernel.process_syscall(service, SYS_GET_CORRELATION, [0; 4], &[]);
+    assert_eq!(current, 0);
+}
+
+/// A client holding a send capability to a service's endpoint:
+/// (kernel, client, service, client_slot, service_slot)
+fn call_setup() -> (System<MockHal>, ProcessId, ProcessId, CapSlot, CapSlot) {
+    let mut kernel = System::new(MockHal::new());
+    let client = kernel.register_process("client");
+    let service = kernel.register_process("service");
+    let (_, service_slot) = kernel.create_endpoint(service).unwrap();
+    let client_slot = kernel
+        .grant_capability(service, service_slot, client, Permissions::write_only())
+        .unwrap();
+    (kernel, client, service, client_slot, service_slot)
+}
+
+#[test]
+fn test_call_is_answered_by_reply() {
+    use zos_ipc::frame::Frame;
+    use zos_ipc::syscall::{SYS_CALL, SYS_CALL_WAIT, SYS_RECV, SYS_REPLY};
+    use zos_ipc::syscall_error::{BUSY, NOT_FOUND};
+
+    let (mut kernel, client, service, client_slot, service_slot) = call_setup();
+    let outsider = kernel.register_process("outsider");
+
+    let (result, _, _) = kernel.process_syscall(client, SYS_CALL, [client_slot, 1, 0, 0], b"ping");
+    assert_eq!(result, 0);
+    let (result, _, _) = kernel.process_syscall(client, SYS_CALL, [client_slot, 1, 0, 0], b"again");
+    assert_eq!(result, BUSY as i64, "One call at a time");
+    let (result, _, _) = kernel.process_syscall(client, SYS_CALL_WAIT, [0; 4], &[]);
+    assert_eq!(result, 0, "Still waiting for the reply");
+
+    // Only the receiver of the request holds the reply capability
+    let caller = client.0 as u32;
+    let (result, _, _) = kernel.process_syscall(service, SYS_REPLY, [caller, 2, 0, 0], b"early");
+    assert_eq!(result, NOT_FOUND as i64);
+    let (_, _, data) = kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    let request = Frame::decode(&data).unwrap();
+    assert_eq!((request.from_pid, request.data), (caller, &b"ping"[..]));
+    let (result, _, _) = kernel.process_syscall(outsider, SYS_REPLY, [caller, 2, 0, 0], b"x");
+    assert_eq!(result, NOT_FOUND as i64);
+
+    let (result, _, _) = kernel.process_syscall(service, SYS_REPLY, [caller, 2, 0, 0], b"pong");
+    assert_eq!(result, 0);
+    let (result, _, _) = kernel.process_syscall(service, SYS_REPLY, [caller, 2, 0, 0], b"pong");
+    assert_eq!(result, NOT_FOUND as i64, "The reply capability is one-shot");
+
+    let (result, _, data) = kernel.process_syscall(client, SYS_CALL_WAIT, [0; 4], &[]);
+    assert_eq!(result, 1);
+    let reply = Frame::decode(&data).unwrap();
+    assert_eq!((reply.from_pid, reply.tag), (service.0 as u32, 2));
+    assert_eq!(reply.data, b"pong");
+
+    // The call is over; the next one may start
+    let (result, _, _) = kernel.process_syscall(client, SYS_CALL_WAIT, [0; 4], &[]);
+    assert_eq!(result, NOT_FOUND as i64);
+    let (result, _, _) = kernel.process_syscall(client, SYS_CALL, [client_slot, 1, 0, 0], b"");
+    assert_eq!(result, 0);
+}
+
+#[test]
+fn test_call_times_out() {
+    use zos_ipc::syscall::{SYS_CALL, SYS_CALL_WAIT, SYS_RECV, SYS_REPLY};
+    use zos_ipc::syscall_error::{NOT_FOUND, TIMED_OUT};
+
+    let (mut kernel, client, service, client_slot, service_slot) = call_setup();
+
+    // 5ms timeout; the service receives the request but is too slow
+    kernel.process_syscall(client, SYS_CALL, [client_slot, 1, 5, 0], b"slow");
+    let (result, _, _) = kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    assert_eq!(result, 1);
+    kernel.hal().time.store(5_000_000, Ordering::SeqCst);
+    let (result, _, _) = kernel.process_syscall(client, SYS_CALL_WAIT, [0; 4], &[]);
+    assert_eq!(result, TIMED_OUT as i64);
+
+    // The late reply is refused rather than answering a later call
+    kernel.process_syscall(client, SYS_CALL, [client_slot, 1, 0, 0], b"next");
+    let caller = client.0 as u32;
+    let (result, _, _) = kernel.process_syscall(service, SYS_REPLY, [caller, 2, 0, 0], b"late");
+    assert_eq!(result, NOT_FOUND as i64);
+
+    // A request whose call timed out while queued is dropped unseen
+    let (mut kernel, client, service, client_slot, service_slot) = call_setup();
+    kernel.process_syscall(client, SYS_CALL, [client_slot, 1, 1, 0], b"expired");
+    kernel.hal().time.store(1_000_000, Ordering::SeqCst);
+    let (result, _, _) = kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    assert_eq!(result, 0);
+}
+
+#[test]
+fn test_call_fails_when_callee_dies() {
+    use zos_ipc::syscall::{SYS_CALL, SYS_CALL_WAIT, SYS_RECV};
+    use zos_ipc::syscall_error::PEER_DIED;
+
+    // The service dies while handling the request
+    let (mut kernel, client, service, client_slot, service_slot) = call_setup();
+    kernel.process_syscall(client, SYS_CALL, [client_slot, 1, 0, 0], b"ping");
+    kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    kernel.kill_process(service);
+    let (result, _, _) = kernel.process_syscall(client, SYS_CALL_WAIT, [0; 4], &[]);
+    assert_eq!(result, PEER_DIED as i64);
+
+    // The service dies before receiving the request
+    let (mut kernel, client, service, client_slot, _) = call_setup();
+    kernel.process_syscall(client, SYS_CALL, [client_slot, 1, 0, 0], b"ping");
+    kernel.kill_process(service);
+    let (result, _, _) = kernel.process_syscall(client, SYS_CALL_WAIT, [0; 4], &[]);
+    assert_eq!(result, PEER_DIED as i64);
+}
+
+#[test]
+fn test_caller_death_ends_its_call() {
+    use zos_ipc::syscall::{SYS_CALL, SYS_RECV, SYS_REPLY};
+    use zos_ipc::syscall_error::NOT_FOUND;
+
+    let (mut kernel, client, service, client_slot, service_slot) = call_setup();
+    kernel.process_syscall(client, SYS_CALL, [client_slot, 1, 0, 0], b"ping");
+    kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    kernel.kill_process(client);
+
+    let caller = client.0 as u32;
+    let (result, _, _) = kernel.process_syscall(service, SYS_REPLY, [caller, 2, 0, 0], b"pong");
+    assert_eq!(result, NOT_FOUND as i64);
+}
+
+#[test]
+fn test_timer_fires_on_owner_endpoint() {
+    use zos_ipc::kernel::MSG_TIMER_FIRED;
+    use zos_ipc::syscall::{SYS_TIMER_CANCEL, SYS_TIMER_CREATE};
+
+    let mut kernel = System::new(MockHal::new());
+    let service = kernel.register_process("service");
+    let other = kernel.register_process("other");
+    let (_, slot) = kernel.create_endpoint(service).unwrap();
+    let other_slot = kernel
+        .grant_capability(service, slot, other, Permissions::write_only())
+        .unwrap();
+
+    // Timers fire only on endpoints the caller can receive on
+    let (result, _, _) =
+        kernel.process_syscall(other, SYS_TIMER_CREATE, [10, 0, other_slot, 0], &[]);
+    assert_eq!(result, -1);
+
+    // Periodic, every 20ms after the first 10ms
+    let (id, _, _) = kernel.process_syscall(service, SYS_TIMER_CREATE, [10, 20, slot, 0], &[]);
+    assert!(id > 0);
+    assert_eq!(kernel.fire_timers(), 0);
+
+    kernel.hal().time.store(10_000_000, Ordering::SeqCst);
+    assert_eq!(kernel.fire_timers(), 1);
+    // Not yet received: the next firing is folded into the queued one
+    kernel.hal().time.store(30_000_000, Ordering::SeqCst);
+    assert_eq!(kernel.fire_timers(), 0);
+
+    let msg = kernel
+        .ipc_receive(service, slot)
+        .expect("receive should succeed")
+        .expect("message should be present");
+    assert_eq!((msg.from, msg.tag), (ProcessId(0), MSG_TIMER_FIRED));
+    assert_eq!(msg.data, (id as u32).to_le_bytes());
+    assert!(kernel.ipc_receive(service, slot).unwrap().is_none());
+
+    // Only the owner cancels; a cancelled timer stays quiet
+    let (result, _, _) = kernel.process_syscall(other, SYS_TIMER_CANCEL, [id as u32, 0, 0, 0], &[]);
+    assert_eq!(result, -1);
+    let (result, _, _) =
+        kernel.process_syscall(service, SYS_TIMER_CANCEL, [id as u32, 0, 0, 0], &[]);
+    assert_eq!(result, 0);
+    kernel.hal().time.store(100_000_000, Ordering::SeqCst);
+    assert_eq!(kernel.fire_timers(), 0);
+
+    // Exiting cancels a process's timers
+    kernel.process_syscall(service, SYS_TIMER_CREATE, [5, 5, slot, 0], &[]);
+    assert_eq!(kernel.kernel.timer_count(service), 1);
+    kernel.kill_process(service);
+    assert_eq!(kernel.kernel.timer_count(service), 0);
+}
+
 // ============================================================================
 // IPC with Capabilities Tests
 // ============================================================================
@@ -853,6 +1313,387 @@ fn test_ipc_has_message() {
     assert_eq!(ep.pending_messages.len(), 1);
 }
 
+#[test]
+fn test_sys_send_cap_installs_capability_on_receive() {
+    use zos_ipc::frame::Frame;
+    use zos_ipc::syscall::{SYS_RECV, SYS_SEND, SYS_SEND_CAP};
+
+    let (mut kernel, client, service, client_slot, service_slot) = call_setup();
+    let (client_ep, client_ep_slot) = kernel.create_endpoint(client).unwrap();
+
+    let mut payload = b"hello".to_vec();
+    payload.extend_from_slice(&client_ep_slot.to_le_bytes());
+    let packed = 5 | (1 << 16);
+    let (result, _, _) =
+        kernel.process_syscall(client, SYS_SEND_CAP, [client_slot, 7, packed, 0], &payload);
+    assert_eq!(result, 0);
+    let (result, _, _) = kernel.process_syscall(client, SYS_SEND, [client_slot, 8, 0, 0], b"next");
+    assert_eq!(result, 0);
+    assert!(kernel.get_cap_space(client).unwrap().get(client_ep_slot).is_none());
+
+    let (result, _, data) =
+        kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    assert_eq!(result, 1);
+    let frame = Frame::decode(&data).unwrap();
+    assert_eq!((frame.tag, frame.data), (7, &b"hello"[..]));
+    assert_eq!(frame.cap_slots.len(), 1);
+    let cap = kernel.get_cap_space(service).unwrap().get(frame.cap_slots[0]).unwrap();
+    assert_eq!(cap.object_id, client_ep.0);
+
+    // Each receive takes exactly one message
+    let (result, _, data) =
+        kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    assert_eq!(result, 1);
+    assert_eq!(Frame::decode(&data).unwrap().tag, 8);
+
+    // The declared lengths must match the payload
+    let (result, _, _) =
+        kernel.process_syscall(client, SYS_SEND_CAP, [client_slot, 7, 5 | (2 << 16), 0], &payload);
+    assert_eq!(result, -1);
+}
+
+// ============================================================================
+// Shared Memory Tests
+// ============================================================================
+
+#[test]
+fn test_shm_region_shared_through_capability() {
+    use zos_ipc::frame::Frame;
+    use zos_ipc::syscall::{
+        SYS_RECV, SYS_SEND_CAP, SYS_SHM_CREATE, SYS_SHM_FETCH, SYS_SHM_FLUSH, SYS_SHM_MAP,
+    };
+    use zos_ipc::syscall_error::{INVALID_ARGUMENT, PERMISSION_DENIED};
+
+    let (mut kernel, client, service, client_slot, service_slot) = call_setup();
+
+    let (shm_slot, _, _) = kernel.process_syscall(client, SYS_SHM_CREATE, [8192, 0, 0, 0], &[]);
+    assert!(shm_slot >= 0);
+    let shm_slot = shm_slot as u32;
+    let cap = kernel.get_cap_space(client).unwrap().get(shm_slot).unwrap().clone();
+    assert_eq!(cap.object_type, ObjectType::Memory);
+    let region = cap.object_id;
+    assert_eq!(kernel.shm_region(region).unwrap().size, 8192);
+
+    // The client maps the region, then hands a read-only capability to the
+    // service
+    let (mapped, _, _) =
+        kernel.process_syscall(client, SYS_SHM_MAP, [shm_slot, 0x1000, 4096, 0], &[]);
+    assert_eq!(mapped, 4096);
+    let read_slot = kernel
+        .derive_capability(client, shm_slot, Permissions::read_only())
+        .unwrap();
+    let mut payload = b"read".to_vec();
+    payload.extend_from_slice(&read_slot.to_le_bytes());
+    let (result, _, _) = kernel.process_syscall(
+        client,
+        SYS_SEND_CAP,
+        [client_slot, 1, 4 | (1 << 16), 0],
+        &payload,
+    );
+    assert_eq!(result, 0);
+    let (_, _, data) = kernel.process_syscall(service, SYS_RECV, [service_slot, 0, 0, 0], &[]);
+    let service_shm = Frame::decode(&data).unwrap().cap_slots[0];
+
+    // The whole region fits the service's window
+    let (mapped, _, _) =
+        kernel.process_syscall(service, SYS_SHM_MAP, [service_shm, 0x4000, 1 << 20, 0], &[]);
+    assert_eq!(mapped, 8192);
+
+    // The client may flush its window, the service only fetch
+    let (result, _, _) = kernel.process_syscall(client, SYS_SHM_FLUSH, [0x1000, 0, 4096, 0], &[]);
+    assert_eq!(result, 0);
+    let access = kernel.shm_access(client, 0x1000, 16, 32, true).unwrap();
+    assert_eq!((access.region, access.addr, access.offset), (region, 0x1010, 16));
+    let (result, _, _) = kernel.process_syscall(client, SYS_SHM_FLUSH, [0x1000, 1, 4096, 0], &[]);
+    assert_eq!(result, INVALID_ARGUMENT as i64);
+    let (result, _, _) = kernel.process_syscall(service, SYS_SHM_FLUSH, [0x4000, 0, 16, 0], &[]);
+    assert_eq!(result, PERMISSION_DENIED as i64);
+    let (result, _, _) = kernel.process_syscall(service, SYS_SHM_FETCH, [0x4000, 0, 8192, 0], &[]);
+    assert_eq!(result, 0);
+}
+
+#[test]
+fn test_shm_region_reaped_once_unreferenced() {
+    use zos_ipc::syscall::{SYS_SHM_CREATE, SYS_SHM_MAP, SYS_SHM_UNMAP};
+    use zos_ipc::syscall_error::{INVALID_ARGUMENT, LIMIT_EXCEEDED, NOT_FOUND};
+    use zos_kernel::{MAX_SHM_REGIONS_PER_PROCESS, MAX_SHM_SIZE};
+
+    let mut kernel = System::new(MockHal::new());
+    let owner = kernel.register_process("owner");
+    let peer = kernel.register_process("peer");
+
+    let (result, _, _) = kernel.process_syscall(owner, SYS_SHM_CREATE, [0, 0, 0, 0], &[]);
+    assert_eq!(result, INVALID_ARGUMENT as i64);
+    let (result, _, _) =
+        kernel.process_syscall(owner, SYS_SHM_CREATE, [MAX_SHM_SIZE + 1, 0, 0, 0], &[]);
+    assert_eq!(result, INVALID_ARGUMENT as i64);
+
+    let slot = kernel.shm_create(owner, 4096).unwrap();
+    let peer_slot = kernel
+        .grant_capability(owner, slot, peer, Permissions::full())
+        .unwrap();
+    kernel.process_syscall(owner, SYS_SHM_MAP, [slot, 0, 4096, 0], &[]);
+    assert_eq!(kernel.shm_regions().len(), 1);
+
+    // The mapping outlives the capability, and the peer's capability
+    // outlives the owner
+    kernel.delete_capability(owner, slot).unwrap();
+    let (result, _, _) = kernel.process_syscall(owner, SYS_SHM_UNMAP, [0, 0, 0, 0], &[]);
+    assert_eq!(result, 0);
+    let (result, _, _) = kernel.process_syscall(owner, SYS_SHM_UNMAP, [0, 0, 0, 0], &[]);
+    assert_eq!(result, NOT_FOUND as i64);
+    assert_eq!(kernel.shm_regions().len(), 1);
+    kernel.kill_process(owner);
+    assert_eq!(kernel.shm_regions().len(), 1);
+
+    let (mapped, _, _) = kernel.process_syscall(peer, SYS_SHM_MAP, [peer_slot, 0, 64, 0], &[]);
+    assert_eq!(mapped, 64);
+    kernel.kill_process(peer);
+    assert!(kernel.shm_regions().is_empty());
+
+    // Dropped regions stop counting against the creator's limit
+    let creator = kernel.register_process("creator");
+    for _ in 0..MAX_SHM_REGIONS_PER_PROCESS {
+        kernel.shm_create(creator, 64).unwrap();
+    }
+    let (result, _, _) = kernel.process_syscall(creator, SYS_SHM_CREATE, [64, 0, 0, 0], &[]);
+    assert_eq!(result, LIMIT_EXCEEDED as i64);
+    kernel.delete_capability(creator, 0).unwrap();
+    let (result, _, _) = kernel.process_syscall(creator, SYS_SHM_CREATE, [64, 0, 0, 0], &[]);
+    assert!(result >= 0);
+}
+
+// ============================================================================
+// Notification Tests
+// ============================================================================
+
+#[test]
+fn test_notification_signals_accumulate_until_waited() {
+    use zos_ipc::syscall::{NOTIFY_BITS, SYS_NOTIFY_CREATE, SYS_SIGNAL, SYS_WAIT};
+    use zos_ipc::syscall_error::{INVALID_ARGUMENT, NOT_FOUND, PERMISSION_DENIED};
+
+    let mut kernel = System::new(MockHal::new());
+    let waiter = kernel.register_process("waiter");
+    let signaller = kernel.register_process("signaller");
+
+    let (slot, _, _) = kernel.process_syscall(waiter, SYS_NOTIFY_CREATE, [0, 0, 0, 0], &[]);
+    assert!(slot >= 0);
+    let slot = slot as u32;
+    let cap = kernel
+        .get_cap_space(waiter)
+        .unwrap()
+        .get(slot)
+        .unwrap()
+        .clone();
+    assert_eq!(cap.object_type, ObjectType::Notification);
+
+    // The signaller may only signal, the waiter both
+    let signal_slot = kernel
+        .grant_capability(waiter, slot, signaller, Permissions::write_only())
+        .unwrap();
+    let (result, _, _) =
+        kernel.process_syscall(signaller, SYS_SIGNAL, [signal_slot, 0b01, 0, 0], &[]);
+    assert_eq!(result, 0);
+    let (result, _, _) =
+        kernel.process_syscall(signaller, SYS_SIGNAL, [signal_slot, 0b10, 0, 0], &[]);
+    assert_eq!(result, 0);
+    let (result, _, _) = kernel.process_syscall(waiter, SYS_SIGNAL, [slot, 0b01, 0, 0], &[]);
+    assert_eq!(result, 0);
+    let (result, _, _) = kernel.process_syscall(signaller, SYS_SIGNAL, [signal_slot, 0, 0, 0], &[]);
+    assert_eq!(result, INVALID_ARGUMENT as i64);
+
+    let (result, _, _) = kernel.process_syscall(signaller, SYS_WAIT, [signal_slot, 0, 0, 0], &[]);
+    assert_eq!(result, PERMISSION_DENIED as i64);
+    let (bits, _, _) = kernel.process_syscall(waiter, SYS_WAIT, [slot, 0, 0, 0], &[]);
+    assert_eq!(bits, 0b11);
+    let (bits, _, _) = kernel.process_syscall(waiter, SYS_WAIT, [slot, 0, 0, 0], &[]);
+    assert_eq!(bits, 0);
+
+    // Bit 31 is reserved
+    let (result, _, _) = kernel.process_syscall(waiter, SYS_SIGNAL, [slot, 1 << 31, 0, 0], &[]);
+    assert_eq!(result, INVALID_ARGUMENT as i64);
+    kernel.process_syscall(waiter, SYS_SIGNAL, [slot, NOTIFY_BITS, 0, 0], &[]);
+    let (bits, _, _) = kernel.process_syscall(waiter, SYS_WAIT, [slot, 0, 0, 0], &[]);
+    assert_eq!(bits, NOTIFY_BITS as i64);
+
+    // Only notification capabilities can be signalled
+    let (_, ep_slot) = kernel.create_endpoint(waiter).unwrap();
+    let (result, _, _) = kernel.process_syscall(waiter, SYS_SIGNAL, [ep_slot, 1, 0, 0], &[]);
+    assert_eq!(result, NOT_FOUND as i64);
+}
+
+#[test]
+fn test_notification_destroyed_with_owner_and_replayed() {
+    use zos_ipc::syscall::{SYS_NOTIFY_CREATE, SYS_SIGNAL};
+    use zos_ipc::syscall_error::{LIMIT_EXCEEDED, NOT_FOUND};
+    use zos_kernel::{replay_and_verify, Replayable, MAX_NOTIFICATIONS_PER_PROCESS};
+
+    let mut kernel = System::new(MockHal::new());
+    let owner = kernel.register_process("owner");
+    let peer = kernel.register_process("peer");
+
+    for _ in 0..MAX_NOTIFICATIONS_PER_PROCESS {
+        let (slot, _, _) = kernel.process_syscall(owner, SYS_NOTIFY_CREATE, [0, 0, 0, 0], &[]);
+        assert!(slot >= 0);
+    }
+    let (result, _, _) = kernel.process_syscall(owner, SYS_NOTIFY_CREATE, [0, 0, 0, 0], &[]);
+    assert_eq!(result, LIMIT_EXCEEDED as i64);
+
+    let peer_slot = kernel
+        .grant_capability(owner, 0, peer, Permissions::full())
+        .unwrap();
+    let (result, _, _) = kernel.process_syscall(peer, SYS_SIGNAL, [peer_slot, 1, 0, 0], &[]);
+    assert_eq!(result, 0);
+    assert!(kernel.verify_state_digest());
+
+    let mut replayed = System::<MockHal>::new_for_replay();
+    replay_and_verify(
+        &mut replayed,
+        kernel.commitlog().commits(),
+        kernel.state_hash(),
+    )
+    .unwrap();
+    assert_eq!(
+        replayed.list_notifications().len(),
+        MAX_NOTIFICATIONS_PER_PROCESS
+    );
+
+    // The peer's capability outlives the notification, but not its use
+    kernel.kill_process(owner);
+    assert!(kernel.list_notifications().is_empty());
+    assert!(kernel.verify_state_digest());
+    let (result, _, _) = kernel.process_syscall(peer, SYS_SIGNAL, [peer_slot, 1, 0, 0], &[]);
+    assert_eq!(result, NOT_FOUND as i64);
+}
+
+// ============================================================================
+// Broadcast Tests
+// ============================================================================
+
+#[test]
+fn test_broadcast_fans_out_to_subscribers() {
+    use zos_ipc::syscall::{
+        SYS_BROADCAST_CREATE, SYS_PUBLISH, SYS_SEND, SYS_SUBSCRIBE, UNSUBSCRIBE,
+    };
+    use zos_ipc::syscall_error::{INVALID_ARGUMENT, PERMISSION_DENIED};
+
+    let mut kernel = System::new(MockHal::new());
+    let publisher = kernel.register_process("publisher");
+    let alice = kernel.register_process("alice");
+    let bob = kernel.register_process("bob");
+
+    let (slot, _, _) = kernel.process_syscall(publisher, SYS_BROADCAST_CREATE, [0, 0, 0, 0], &[]);
+    assert!(slot >= 0);
+    let slot = slot as u32;
+
+    // Listeners may subscribe but not publish
+    let mut listeners = Vec::new();
+    for pid in [alice, bob] {
+        let broadcast_slot = kernel
+            .grant_capability(publisher, slot, pid, Permissions::read_only())
+            .unwrap();
+        let (_, inbox) = kernel.create_endpoint(pid).unwrap();
+        let (result, _, _) =
+            kernel.process_syscall(pid, SYS_SUBSCRIBE, [broadcast_slot, inbox, 0, 0], &[]);
+        assert_eq!(result, 0);
+        listeners.push((pid, broadcast_slot, inbox));
+    }
+    let (alice_broadcast, alice_inbox) = (listeners[0].1, listeners[0].2);
+    let (result, _, _) =
+        kernel.process_syscall(alice, SYS_PUBLISH, [alice_broadcast, 7, 0, 0], b"no");
+    assert_eq!(result, PERMISSION_DENIED as i64);
+
+    let (delivered, _, _) =
+        kernel.process_syscall(publisher, SYS_PUBLISH, [slot, 7, 0, 0], b"login");
+    assert_eq!(delivered, 2);
+    for &(pid, _, inbox) in &listeners {
+        let msg = kernel.ipc_receive(pid, inbox).unwrap().unwrap();
+        assert_eq!(
+            (msg.from, msg.tag, msg.data.as_slice()),
+            (publisher, 7, &b"login"[..])
+        );
+    }
+
+    // A broadcast endpoint takes no ordinary sends and cannot subscribe
+    let (result, _, _) = kernel.process_syscall(publisher, SYS_SEND, [slot, 7, 0, 0], b"x");
+    assert_ne!(result, 0);
+    let (result, _, _) = kernel.process_syscall(
+        alice,
+        SYS_SUBSCRIBE,
+        [alice_broadcast, alice_broadcast, 0, 0],
+        &[],
+    );
+    assert_eq!(result, INVALID_ARGUMENT as i64);
+    let (result, _, _) = kernel.process_syscall(alice, SYS_PUBLISH, [alice_inbox, 7, 0, 0], b"x");
+    assert_eq!(result, INVALID_ARGUMENT as i64);
+
+    let (result, _, _) = kernel.process_syscall(
+        alice,
+        SYS_SUBSCRIBE,
+        [alice_broadcast, UNSUBSCRIBE, 0, 0],
+        &[],
+    );
+    assert_eq!(result, 0);
+    let (delivered, _, _) =
+        kernel.process_syscall(publisher, SYS_PUBLISH, [slot, 8, 0, 0], b"time");
+    assert_eq!(delivered, 1);
+    assert!(kernel.ipc_receive(alice, alice_inbox).unwrap().is_none());
+    assert!(kernel.verify_state_digest());
+}
+
+#[test]
+fn test_broadcast_drops_for_lagging_subscribers() {
+    use zos_ipc::syscall::{SYS_BROADCAST_CREATE, SYS_PUBLISH, SYS_SUBSCRIBE};
+    use zos_kernel::{EndpointId, MAX_BROADCAST_BACKLOG};
+
+    let mut kernel = System::new(MockHal::new());
+    let publisher = kernel.register_process("publisher");
+    let slow = kernel.register_process("slow");
+    let fast = kernel.register_process("fast");
+
+    let (slot, _, _) = kernel.process_syscall(publisher, SYS_BROADCAST_CREATE, [0, 0, 0, 0], &[]);
+    let slot = slot as u32;
+    let broadcast_id = EndpointId(
+        kernel
+            .get_cap_space(publisher)
+            .unwrap()
+            .get(slot)
+            .unwrap()
+            .object_id,
+    );
+    let mut inboxes = Vec::new();
+    for pid in [slow, fast] {
+        let broadcast_slot = kernel
+            .grant_capability(publisher, slot, pid, Permissions::read_only())
+            .unwrap();
+        let (_, inbox) = kernel.create_endpoint(pid).unwrap();
+        kernel.process_syscall(pid, SYS_SUBSCRIBE, [broadcast_slot, inbox, 0, 0], &[]);
+        inboxes.push(inbox);
+    }
+
+    // The slow subscriber never receives; the fast one keeps up
+    for _ in 0..MAX_BROADCAST_BACKLOG + 3 {
+        let (delivered, _, _) =
+            kernel.process_syscall(publisher, SYS_PUBLISH, [slot, 1, 0, 0], b"quota");
+        assert!(delivered >= 1);
+        assert!(kernel.ipc_receive(fast, inboxes[1]).unwrap().is_some());
+    }
+    let subscribers = kernel.broadcast_subscribers(broadcast_id).unwrap();
+    let dropped: Vec<u64> = subscribers.iter().map(|s| s.dropped).collect();
+    assert_eq!(dropped, [3, 0]);
+    for _ in 0..MAX_BROADCAST_BACKLOG {
+        assert!(kernel.ipc_receive(slow, inboxes[0]).unwrap().is_some());
+    }
+    assert!(kernel.ipc_receive(slow, inboxes[0]).unwrap().is_none());
+
+    // Subscriptions end with the subscriber, and the broadcast with its owner
+    kernel.kill_process(slow);
+    assert_eq!(kernel.broadcast_subscribers(broadcast_id).unwrap().len(), 1);
+    kernel.kill_process(publisher);
+    assert!(kernel.broadcast_subscribers(broadcast_id).is_none());
+    assert!(kernel.verify_state_digest());
+}
+
 // ============================================================================
 // System Tests - fault_process, syscall dispatch
 // ============================================================================
@@ -953,6 +1794,530 @@ fn test_syscall_dispatch_exit() {
     assert!(result >= 0 || result == -1, "Exit should complete");
 }
 
+#[test]
+fn test_exit_is_reported_to_init() {
+    let hal = MockHal::new();
+    let mut kernel = System::new(hal);
+
+    let init = kernel.register_process("init");
+    let (_, init_slot) = kernel.create_endpoint(init).unwrap();
+    let exiting = kernel.register_process("service");
+    let killed = kernel.register_process("worker");
+
+    // SYS_EXIT = 0x11, reporting the process's own code
+    kernel.process_syscall(exiting, 0x11, [3, 0, 0, 0], &[]);
+    kernel.kill_process(killed);
+
+    let expected = [(exiting, 3), (killed, zos_kernel::KILLED_EXIT_CODE)];
+    for (pid, code) in expected {
+        let msg = kernel
+            .ipc_receive(init, init_slot)
+            .unwrap()
+            .expect("exit notification");
+        assert_eq!(msg.from, ProcessId(0));
+        assert_eq!(msg.tag, zos_ipc::init::MSG_PROCESS_EXITED);
+        assert_eq!(msg.data[..4], (pid.0 as u32).to_le_bytes());
+        assert_eq!(msg.data[4..], code.to_le_bytes());
+    }
+    assert!(kernel.ipc_receive(init, init_slot).unwrap().is_none());
+}
+
+#[test]
+fn test_exit_syscall_replays() {
+    use zos_kernel::{replay_and_verify, Replayable};
+
+    let mut kernel = System::new(MockHal::new());
+    kernel.register_process("init");
+    let exiting = kernel.register_process("service");
+    kernel.create_endpoint(exiting).unwrap();
+
+    // SYS_EXIT = 0x11: the process is gone, not left a zombie
+    kernel.process_syscall(exiting, 0x11, [3, 0, 0, 0], &[]);
+    assert!(kernel.get_process(exiting).is_none());
+
+    let mut replayed = System::<MockHal>::new_for_replay();
+    replay_and_verify(
+        &mut replayed,
+        kernel.commitlog().commits(),
+        kernel.state_hash(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_set_priority_needs_authority_to_raise() {
+    use zos_ipc::priority::{BACKGROUND, INTERACTIVE, SYSTEM_SERVICE};
+    use zos_ipc::syscall::SYS_SET_PRIORITY;
+    use zos_ipc::syscall_error::{INVALID_ARGUMENT, NOT_FOUND, PERMISSION_DENIED};
+    use zos_kernel::{replay_and_verify, Priority, Replayable};
+
+    let mut kernel = System::new(MockHal::new());
+    let init = kernel.register_process("init");
+    let indexer = kernel.register_process("indexer");
+    let terminal = kernel.register_process("terminal");
+    assert_eq!(
+        kernel.get_process(init).unwrap().priority,
+        Priority::SystemService
+    );
+    assert_eq!(
+        kernel.get_process(indexer).unwrap().priority,
+        Priority::Interactive
+    );
+
+    // Stepping aside is always allowed; taking it back is not
+    let lower = [indexer.0 as u32, BACKGROUND as u32, 0, 0];
+    let (result, _, _) = kernel.process_syscall(indexer, SYS_SET_PRIORITY, lower, &[]);
+    assert_eq!(result, 0);
+    assert_eq!(
+        kernel.get_process(indexer).unwrap().priority,
+        Priority::Background
+    );
+    let raise = [indexer.0 as u32, INTERACTIVE as u32, 0, 0];
+    let (result, _, _) = kernel.process_syscall(indexer, SYS_SET_PRIORITY, raise, &[]);
+    assert_eq!(result, PERMISSION_DENIED as i64);
+
+    // Nor may one process demote another without a Process capability
+    let demote = [terminal.0 as u32, BACKGROUND as u32, 0, 0];
+    let (result, _, _) = kernel.process_syscall(indexer, SYS_SET_PRIORITY, demote, &[]);
+    assert_eq!(result, PERMISSION_DENIED as i64);
+
+    // Init may set any priority
+    let promote = [terminal.0 as u32, SYSTEM_SERVICE as u32, 0, 0];
+    let (result, _, _) = kernel.process_syscall(init, SYS_SET_PRIORITY, promote, &[]);
+    assert_eq!(result, 0);
+    assert_eq!(
+        kernel.get_process(terminal).unwrap().priority,
+        Priority::SystemService
+    );
+
+    let (result, _, _) = kernel.process_syscall(init, SYS_SET_PRIORITY, [99, 1, 0, 0], &[]);
+    assert_eq!(result, NOT_FOUND as i64);
+    let (result, _, _) =
+        kernel.process_syscall(init, SYS_SET_PRIORITY, [terminal.0 as u32, 3, 0, 0], &[]);
+    assert_eq!(result, INVALID_ARGUMENT as i64);
+
+    assert!(kernel.verify_state_digest());
+    let mut replayed = System::<MockHal>::new_for_replay();
+    replay_and_verify(
+        &mut replayed,
+        kernel.commitlog().commits(),
+        kernel.state_hash(),
+    )
+    .unwrap();
+    assert_eq!(
+        replayed.get_process(indexer).unwrap().priority,
+        Priority::Background
+    );
+}
+
+#[test]
+fn test_set_memory_limit_needs_authority_to_raise() {
+    use zos_ipc::syscall::SYS_SET_MEMORY_LIMIT;
+    use zos_ipc::syscall_error::{INVALID_ARGUMENT, NOT_FOUND, PERMISSION_DENIED};
+    use zos_kernel::{replay_and_verify, Replayable, DEFAULT_MEMORY_LIMIT};
+
+    let mut kernel = System::new(MockHal::new());
+    let init = kernel.register_process("init");
+    let clock = kernel.register_process("clock");
+    let terminal = kernel.register_process("terminal");
+    assert_eq!(
+        kernel.get_process(clock).unwrap().memory_limit,
+        DEFAULT_MEMORY_LIMIT
+    );
+
+    // Limits are in KiB; a process may lower its own but not raise it
+    let lower = [clock.0 as u32, 16 * 1024, 0, 0];
+    let (result, _, _) = kernel.process_syscall(clock, SYS_SET_MEMORY_LIMIT, lower, &[]);
+    assert_eq!(result, 0);
+    assert_eq!(
+        kernel.get_process(clock).unwrap().memory_limit,
+        16 * 1024 * 1024
+    );
+    let raise = [clock.0 as u32, 32 * 1024, 0, 0];
+    let (result, _, _) = kernel.process_syscall(clock, SYS_SET_MEMORY_LIMIT, raise, &[]);
+    assert_eq!(result, PERMISSION_DENIED as i64);
+
+    // Nor may one process squeeze another without a Process capability
+    let squeeze = [terminal.0 as u32, 1024, 0, 0];
+    let (result, _, _) = kernel.process_syscall(clock, SYS_SET_MEMORY_LIMIT, squeeze, &[]);
+    assert_eq!(result, PERMISSION_DENIED as i64);
+
+    // Init may set any limit, but not below what the process already has
+    let (result, _, _) = kernel.process_syscall(init, SYS_SET_MEMORY_LIMIT, raise, &[]);
+    assert_eq!(result, 0);
+    let tiny = [terminal.0 as u32, 1, 0, 0];
+    let (result, _, _) = kernel.process_syscall(init, SYS_SET_MEMORY_LIMIT, tiny, &[]);
+    assert_eq!(result, INVALID_ARGUMENT as i64);
+    let (result, _, _) = kernel.process_syscall(init, SYS_SET_MEMORY_LIMIT, [99, 1024, 0, 0], &[]);
+    assert_eq!(result, NOT_FOUND as i64);
+
+    assert!(kernel.verify_state_digest());
+    let mut replayed = System::<MockHal>::new_for_replay();
+    replay_and_verify(
+        &mut replayed,
+        kernel.commitlog().commits(),
+        kernel.state_hash(),
+    )
+    .unwrap();
+    assert_eq!(
+        replayed.get_process(clock).unwrap().memory_limit,
+        32 * 1024 * 1024
+    );
+}
+
+#[test]
+fn test_process_over_memory_limit_is_terminated() {
+    use zos_ipc::init::{MSG_PROCESS_EXITED, MSG_PROCESS_OOM};
+    use zos_ipc::syscall::SYS_SET_MEMORY_LIMIT;
+    use zos_kernel::{CommitType, FAULT_OUT_OF_MEMORY};
+
+    let mut kernel = System::new(MockHal::new());
+    let init = kernel.register_process("init");
+    let (_, init_slot) = kernel.create_endpoint(init).unwrap();
+    let leaky = kernel.register_process("leaky");
+    let limit = 1024 * 1024;
+    let args = [leaky.0 as u32, 1024, 0, 0];
+    kernel.process_syscall(leaky, SYS_SET_MEMORY_LIMIT, args, &[]);
+
+    // Simulated allocations stop at the limit
+    let size = kernel.get_process(leaky).unwrap().metrics.memory_size;
+    assert_eq!(
+        kernel.allocate_memory(leaky, limit),
+        Err(KernelError::Hal(HalError::OutOfMemory))
+    );
+    assert_eq!(kernel.allocate_memory(leaky, limit - size), Ok(limit));
+
+    // Growing to the limit is fine, growing past it is fatal
+    assert!(!kernel.update_process_memory(leaky, limit));
+    assert!(kernel.update_process_memory(leaky, 2 * limit));
+    assert!(kernel.get_process(leaky).is_none());
+
+    let oom = kernel.ipc_receive(init, init_slot).unwrap().unwrap();
+    assert_eq!(oom.from, ProcessId(0));
+    assert_eq!(oom.tag, MSG_PROCESS_OOM);
+    assert_eq!(oom.data[..4], (leaky.0 as u32).to_le_bytes());
+    assert_eq!(oom.data[4..12], (2 * limit as u64).to_le_bytes());
+    assert_eq!(oom.data[12..], (limit as u64).to_le_bytes());
+    let exited = kernel.ipc_receive(init, init_slot).unwrap().unwrap();
+    assert_eq!(exited.tag, MSG_PROCESS_EXITED);
+    assert_eq!(exited.data[..4], (leaky.0 as u32).to_le_bytes());
+
+    assert!(kernel.commitlog().commits().iter().any(|c| matches!(
+        c.commit_type,
+        CommitType::ProcessFaulted { pid, reason, .. }
+            if pid == leaky.0 && reason == FAULT_OUT_OF_MEMORY
+    )));
+    assert!(kernel.verify_state_digest());
+}
+
+#[test]
+fn test_proc_inspection_needs_debugger_cap() {
+    use zos_ipc::syscall::{PROC_READ_MEM_MAX, SYS_PROC_READ_MEM, SYS_PROC_STATE, SYS_YIELD};
+    use zos_ipc::syscall_error::{INVALID_ARGUMENT, NOT_FOUND, PERMISSION_DENIED};
+    use zos_kernel::{replay_and_verify, Replayable};
+
+    let mut kernel = System::new(MockHal::new());
+    kernel.register_process("init");
+    let permission = kernel.register_process("permission");
+    let debugger = kernel.register_process("debugger");
+    let stuck = kernel.register_process("stuck");
+    kernel.create_endpoint(stuck).unwrap();
+    kernel.update_process_memory(stuck, 65536);
+    kernel.process_syscall(stuck, SYS_YIELD, [0; 4], &[]);
+
+    // Nothing can be inspected without a Debugger capability
+    let state = [stuck.0 as u32, 0, 0, 0];
+    let (result, _, data) = kernel.process_syscall(debugger, SYS_PROC_STATE, state, &[]);
+    assert_eq!(result, PERMISSION_DENIED as i64);
+    assert!(data.is_empty());
+
+    // PermissionService holds the only one and grants it on
+    let root = kernel.create_debugger_capability(permission).unwrap();
+    let cap = kernel.get_cap_space(permission).unwrap().get(root).unwrap();
+    assert_eq!(cap.object_type, ObjectType::Debugger);
+    kernel
+        .grant_capability(permission, root, debugger, Permissions::read_only())
+        .unwrap();
+
+    let (result, _, data) = kernel.process_syscall(debugger, SYS_PROC_STATE, state, &[]);
+    assert_eq!(result, 0);
+    assert_eq!(data[2], 1, "left waiting by its yield");
+    assert_eq!(data[3..7], SYS_YIELD.to_le_bytes());
+    assert_eq!(data[7..15], 65536u64.to_le_bytes());
+    assert_eq!(data[23..31], 1u64.to_le_bytes());
+    assert_eq!(data[75..79], 1u32.to_le_bytes());
+    assert_eq!(&data[83..], b"stuck");
+
+    let read = [stuck.0 as u32, 16, 8, 0];
+    let (result, _, data) = kernel.process_syscall(debugger, SYS_PROC_READ_MEM, read, &[]);
+    assert_eq!(result, 8);
+    assert_eq!(data, (16u8..24).collect::<Vec<_>>());
+
+    // Reads stay inside the process's memory and under the maximum
+    for args in [
+        [stuck.0 as u32, 65536 - 4, 8, 0],
+        [stuck.0 as u32, 0, PROC_READ_MEM_MAX + 1, 0],
+    ] {
+        let (result, _, _) = kernel.process_syscall(debugger, SYS_PROC_READ_MEM, args, &[]);
+        assert_eq!(result, INVALID_ARGUMENT as i64);
+    }
+    let (result, _, _) = kernel.process_syscall(debugger, SYS_PROC_READ_MEM, [99, 0, 8, 0], &[]);
+    assert_eq!(result, NOT_FOUND as i64);
+
+    assert!(kernel.verify_state_digest());
+    let mut replayed = System::<MockHal>::new_for_replay();
+    replay_and_verify(
+        &mut replayed,
+        kernel.commitlog().commits(),
+        kernel.state_hash(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_process_groups_and_group_kill() {
+    use zos_ipc::syscall::{KILL_GROUP, SYS_KILL, SYS_REGISTER_PROCESS, SYS_SETPGID};
+    use zos_ipc::syscall_error::{NOT_FOUND, PERMISSION_DENIED};
+    use zos_kernel::{replay_and_verify, Replayable};
+
+    let mut kernel = System::new(MockHal::new());
+    let init = kernel.register_process("init");
+    let terminal = kernel.register_process("terminal");
+    let editor = kernel.register_process("editor");
+    assert_eq!(kernel.get_process(terminal).unwrap().pgid, terminal);
+
+    // Init spawns a pipeline into the terminal's group
+    let in_terminal_group = [3, terminal.0 as u32, 0, 0];
+    let (cat, _, _) = kernel.process_syscall(init, SYS_REGISTER_PROCESS, in_terminal_group, b"cat");
+    let (grep, _, _) = kernel.process_syscall(
+        init,
+        SYS_REGISTER_PROCESS,
+        [4, terminal.0 as u32, 0, 0],
+        b"grep",
+    );
+    let (cat, grep) = (ProcessId(cat as u64), ProcessId(grep as u64));
+    assert_eq!(kernel.get_process(cat).unwrap().pgid, terminal);
+    assert_eq!(kernel.get_process(grep).unwrap().pgid, terminal);
+
+    // Outsiders can neither join the group nor kill it
+    let join = [editor.0 as u32, terminal.0 as u32, 0, 0];
+    let (result, _, _) = kernel.process_syscall(editor, SYS_SETPGID, join, &[]);
+    assert_eq!(result, PERMISSION_DENIED as i64);
+    let kill_group = [terminal.0 as u32, KILL_GROUP, 0, 0];
+    let (result, _, _) = kernel.process_syscall(editor, SYS_KILL, kill_group, &[]);
+    assert_eq!(result, PERMISSION_DENIED as i64);
+
+    // The terminal may move a member into a group of its own
+    let (result, _, _) =
+        kernel.process_syscall(terminal, SYS_SETPGID, [grep.0 as u32, 0, 0, 0], &[]);
+    assert_eq!(result, grep.0 as i64);
+    assert_eq!(kernel.get_process(grep).unwrap().pgid, grep);
+    // It is then out of the terminal's reach
+    let (result, _, _) = kernel.process_syscall(
+        terminal,
+        SYS_SETPGID,
+        [grep.0 as u32, terminal.0 as u32, 0, 0],
+        &[],
+    );
+    assert_eq!(result, PERMISSION_DENIED as i64);
+    let (result, _, _) =
+        kernel.process_syscall(terminal, SYS_SETPGID, [terminal.0 as u32, 99, 0, 0], &[]);
+    assert_eq!(result, NOT_FOUND as i64);
+
+    // Killing its own group spares the terminal and the moved process
+    let (result, _, _) = kernel.process_syscall(terminal, SYS_KILL, kill_group, &[]);
+    assert_eq!(result, 1);
+    assert!(kernel.get_process(cat).is_none());
+    assert!(kernel.get_process(terminal).is_some());
+    assert!(kernel.get_process(grep).is_some());
+    let (result, _, _) = kernel.process_syscall(init, SYS_KILL, [99, KILL_GROUP, 0, 0], &[]);
+    assert_eq!(result, NOT_FOUND as i64);
+
+    assert!(kernel.verify_state_digest());
+    let mut replayed = System::<MockHal>::new_for_replay();
+    replay_and_verify(
+        &mut replayed,
+        kernel.commitlog().commits(),
+        kernel.state_hash(),
+    )
+    .unwrap();
+    assert_eq!(replayed.get_process(grep).unwrap().pgid, grep);
+}
+
+#[test]
+fn test_received_payload_buffers_are_reused() {
+    use zos_kernel::{SYS_RECV, SYS_SEND};
+
+    let hal = MockHal::new();
+    let mut kernel = System::new(hal);
+
+    let sender = kernel.register_process("sender");
+    let receiver = kernel.register_process("receiver");
+    let (_, recv_slot) = kernel.create_endpoint(receiver).unwrap();
+    let send_slot = kernel
+        .grant_capability(receiver, recv_slot, sender, Permissions::write_only())
+        .unwrap();
+
+    for i in 0..3u8 {
+        let (result, _, _) =
+            kernel.process_syscall(sender, SYS_SEND, [send_slot, 7, 0, 0], &[i; 16]);
+        assert_eq!(result, 0);
+        let (result, _, data) =
+            kernel.process_syscall(receiver, SYS_RECV, [recv_slot, 0, 0, 0], &[]);
+        assert_eq!(result, 1);
+        assert_eq!(data[zos_ipc::frame::Frame::MIN_HEADER_LEN..], [i; 16]);
+    }
+
+    // Only the first send allocates; later sends reuse the received buffer
+    let pool = kernel.get_system_metrics().message_pool;
+    assert_eq!(pool.misses, 1);
+    assert_eq!(pool.hits, 2);
+    assert_eq!(pool.recycled, 3);
+}
+
+// ============================================================================
+// Wait-queue fairness
+// ============================================================================
+
+/// An endpoint owned by `receiver`, filled to capacity by `filler`, with
+/// `senders` holding send capabilities to it.
+fn full_endpoint(
+    kernel: &mut System<MockHal>,
+    senders: &[ProcessId],
+) -> (ProcessId, CapSlot, Vec<CapSlot>) {
+    let receiver = kernel.register_process("receiver");
+    let (_, recv_slot) = kernel.create_endpoint(receiver).unwrap();
+    let filler = kernel.register_process("filler");
+    let filler_slot = kernel
+        .grant_capability(receiver, recv_slot, filler, Permissions::write_only())
+        .unwrap();
+    for _ in 0..zos_kernel::MAX_QUEUED_MESSAGES {
+        kernel.ipc_send(filler, filler_slot, 0, Vec::new()).unwrap();
+    }
+    let slots = senders
+        .iter()
+        .map(|&pid| {
+            kernel
+                .grant_capability(receiver, recv_slot, pid, Permissions::write_only())
+                .unwrap()
+        })
+        .collect();
+    (receiver, recv_slot, slots)
+}
+
+#[test]
+fn test_blocked_senders_get_free_slots_in_fifo_order() {
+    let hal = MockHal::new();
+    let mut kernel = System::new(hal);
+    let a = kernel.register_process("a");
+    let b = kernel.register_process("b");
+    let c = kernel.register_process("c");
+    let late = kernel.register_process("late");
+    let (receiver, recv_slot, slots) = full_endpoint(&mut kernel, &[a, b, c, late]);
+    let send = |kernel: &mut System<MockHal>, i: usize| {
+        kernel.ipc_send([a, b, c, late][i], slots[i], i as u32 + 1, Vec::new())
+    };
+
+    // a, b and c block in that order
+    for i in 0..3 {
+        assert_eq!(send(&mut kernel, i), Err(KernelError::WouldBlock));
+    }
+
+    // Each freed slot goes to the longest waiter, whoever retries first
+    for turn in 0..3 {
+        kernel.ipc_receive(receiver, recv_slot).unwrap().unwrap();
+        for i in (turn + 1..4).rev() {
+            assert_eq!(send(&mut kernel, i), Err(KernelError::WouldBlock));
+        }
+        assert_eq!(send(&mut kernel, turn), Ok(()));
+    }
+
+    // The latecomer joined the line behind c and is next
+    kernel.ipc_receive(receiver, recv_slot).unwrap().unwrap();
+    assert_eq!(send(&mut kernel, 3), Ok(()));
+
+    let tags: Vec<u32> = core::iter::from_fn(|| kernel.ipc_receive(receiver, recv_slot).unwrap())
+        .map(|msg| msg.tag)
+        .filter(|&tag| tag != 0)
+        .collect();
+    assert_eq!(tags, [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_waiting_receivers_are_served_in_fifo_order() {
+    let hal = MockHal::new();
+    let mut kernel = System::new(hal);
+    let owner = kernel.register_process("owner");
+    let (_, owner_slot) = kernel.create_endpoint(owner).unwrap();
+    let sender = kernel.register_process("sender");
+    let send_slot = kernel
+        .grant_capability(owner, owner_slot, sender, Permissions::write_only())
+        .unwrap();
+    let mut receivers = vec![(owner, owner_slot)];
+    for name in ["worker1", "worker2"] {
+        let pid = kernel.register_process(name);
+        let slot = kernel
+            .grant_capability(owner, owner_slot, pid, Permissions::read_only())
+            .unwrap();
+        receivers.push((pid, slot));
+    }
+    let recv = |kernel: &mut System<MockHal>, i: usize| {
+        let (pid, slot) = receivers[i];
+        kernel.ipc_receive(pid, slot).unwrap().map(|msg| msg.tag)
+    };
+
+    // Line: worker2, owner, worker1
+    for i in [2, 0, 1] {
+        assert_eq!(recv(&mut kernel, i), None);
+    }
+
+    kernel.ipc_send(sender, send_slot, 1, Vec::new()).unwrap();
+    assert_eq!(recv(&mut kernel, 1), None);
+    assert_eq!(recv(&mut kernel, 0), None);
+    assert_eq!(recv(&mut kernel, 2), Some(1));
+
+    // Two messages for the two remaining waiters, in their order
+    kernel.ipc_send(sender, send_slot, 2, Vec::new()).unwrap();
+    kernel.ipc_send(sender, send_slot, 3, Vec::new()).unwrap();
+    assert_eq!(recv(&mut kernel, 2), None);
+    assert_eq!(recv(&mut kernel, 0), Some(2));
+    assert_eq!(recv(&mut kernel, 1), Some(3));
+}
+
+#[test]
+fn test_stalled_or_exited_waiters_lose_their_place() {
+    let hal = MockHal::new();
+    let mut kernel = System::new(hal);
+    let stalled = kernel.register_process("stalled");
+    let exited = kernel.register_process("exited");
+    let patient = kernel.register_process("patient");
+    let (receiver, recv_slot, slots) = full_endpoint(&mut kernel, &[stalled, exited, patient]);
+
+    for (pid, slot) in [(stalled, slots[0]), (exited, slots[1]), (patient, slots[2])] {
+        assert_eq!(
+            kernel.ipc_send(pid, slot, 1, Vec::new()),
+            Err(KernelError::WouldBlock)
+        );
+    }
+    kernel.ipc_receive(receiver, recv_slot).unwrap().unwrap();
+    kernel.kill_process(exited);
+
+    // The stalled sender still holds its turn until it times out
+    kernel
+        .hal()
+        .time
+        .store(zos_kernel::WAITER_TIMEOUT_NS / 2, Ordering::SeqCst);
+    assert_eq!(
+        kernel.ipc_send(patient, slots[2], 1, Vec::new()),
+        Err(KernelError::WouldBlock)
+    );
+    kernel
+        .hal()
+        .time
+        .store(zos_kernel::WAITER_TIMEOUT_NS, Ordering::SeqCst);
+    assert_eq!(kernel.ipc_send(patient, slots[2], 1, Vec::new()), Ok(()));
+}
+
 #[test]
 fn test_syscall_dispatch_create_endpoint() {
     let hal = MockHal::new();
@@ -1096,3 +2461,143 @@ fn test_syslog_records_syscalls() {
         "Should record syscall in syslog"
     );
 }
+
+#[test]
+fn test_direct_grant_is_syslogged_from_supervisor() {
+    use zos_ipc::syscall::SYS_CAP_GRANT;
+    use zos_kernel::SysEventType;
+
+    let mut kernel = System::new(MockHal::new());
+    let owner = kernel.register_process("owner");
+    let recipient = kernel.register_process("recipient");
+    let (_eid, owner_slot) = kernel.create_endpoint(owner).unwrap();
+
+    let events_before = kernel.syslog().len();
+    kernel
+        .grant_capability(owner, owner_slot, recipient, Permissions::read_only())
+        .unwrap();
+
+    let events = kernel.syslog().events();
+    assert_eq!(events.len(), events_before + 2);
+    let request = &events[events_before];
+    assert_eq!(request.sender, zos_ipc::pid::SUPERVISOR as u64);
+    assert!(matches!(
+        request.event_type,
+        SysEventType::Request {
+            syscall_num: SYS_CAP_GRANT,
+            ..
+        }
+    ));
+    assert!(matches!(
+        events[events_before + 1].event_type,
+        SysEventType::Response { result: 0, .. }
+    ));
+}
+
+// ============================================================================
+// Runtime Invariant Tests (debug-invariants feature)
+// ============================================================================
+
+#[cfg(feature = "debug-invariants")]
+#[test]
+#[should_panic(expected = "invariant no-direct-kernel-access violated")]
+fn test_direct_kernel_mutation_is_caught_at_the_gateway() {
+    let mut kernel = System::new(MockHal::new());
+    let owner = kernel.register_process("owner");
+    let (_eid, slot) = kernel.create_endpoint(owner).unwrap();
+
+    // Bypass the System: the capability goes, but no commit records it
+    let _ = kernel.kernel.delete_capability(owner, slot, 0);
+
+    kernel.process_syscall(owner, 0x00, [0, 0, 0, 0], &[]);
+}
+
+#[cfg(feature = "debug-invariants")]
+#[test]
+#[should_panic(expected = "invariant grants-are-syslogged violated outside any syscall")]
+fn test_grant_without_syslog_request_is_caught() {
+    use zos_kernel::Syscall;
+
+    let mut kernel = System::new(MockHal::new());
+    let owner = kernel.register_process("owner");
+    let recipient = kernel.register_process("recipient");
+    let (_eid, slot) = kernel.create_endpoint(owner).unwrap();
+
+    // The legacy syscall API records commits but never reaches the SysLog
+    kernel.handle_syscall(
+        owner,
+        Syscall::CapGrant {
+            from_slot: slot,
+            to_pid: recipient,
+            permissions: Permissions::read_only(),
+        },
+    );
+}
+
+#[cfg(feature = "debug-invariants")]
+#[test]
+fn test_gateway_checks_pass_for_normal_traffic() {
+    use zos_ipc::syscall::{SYS_CAP_GRANT, SYS_CREATE_ENDPOINT_FOR};
+
+    let mut kernel = System::new(MockHal::new());
+    let init = kernel.register_process_with_pid(ProcessId(1), "init");
+    let service = kernel.register_process("service");
+    let (_eid, slot) = kernel.create_endpoint(service).unwrap();
+
+    let (result, _, _) = kernel.process_syscall(
+        init,
+        SYS_CREATE_ENDPOINT_FOR,
+        [service.0 as u32, 0, 0, 0],
+        &[],
+    );
+    assert!(result >= 0);
+    let (result, _, _) = kernel.process_syscall(
+        service,
+        SYS_CAP_GRANT,
+        [
+            slot,
+            init.0 as u32,
+            Permissions::read_only().to_byte() as u32,
+            0,
+        ],
+        &[],
+    );
+    assert!(result >= 0);
+    assert!(kernel.verify_state_digest());
+}
+
+#[test]
+fn test_syscalls_account_run_time_and_wakeups() {
+    use zos_ipc::syscall::{PS_USAGE, SYS_PS, SYS_YIELD};
+
+    let mut kernel = System::new(MockHal::new());
+    let worker = kernel.register_process("worker");
+
+    kernel.hal().time.store(1_000, Ordering::SeqCst);
+    kernel.process_syscall(worker, SYS_PS, [0; 4], &[]);
+    // Running from 1_000 until it yields at 4_000
+    kernel.hal().time.store(4_000, Ordering::SeqCst);
+    kernel.process_syscall(worker, SYS_YIELD, [0; 4], &[]);
+    // Descheduled until 10_000, then runs until 12_000
+    kernel.hal().time.store(10_000, Ordering::SeqCst);
+    kernel.process_syscall(worker, SYS_PS, [0; 4], &[]);
+    kernel.hal().time.store(12_000, Ordering::SeqCst);
+    let (result, _, data) = kernel.process_syscall(worker, SYS_PS, [PS_USAGE, 0, 0, 0], &[]);
+    assert_eq!(result, 0);
+
+    let metrics = &kernel.get_process(worker).unwrap().metrics;
+    assert_eq!(metrics.syscall_count, 4);
+    assert_eq!(metrics.run_time_ns, 5_000);
+    assert_eq!(metrics.wakeups, 1);
+
+    // The usage record follows the worker's name
+    let u64_at = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+    assert_eq!(u32::from_le_bytes(data[0..4].try_into().unwrap()), 1);
+    assert_eq!(u32::from_le_bytes(data[4..8].try_into().unwrap()), worker.0 as u32);
+    assert_eq!(&data[10..16], b"worker");
+    let usage = 16 + 2;
+    assert_eq!(u64_at(usage + 8), 3); // syscalls accounted before this one
+    assert_eq!(u64_at(usage + 32), 3_000);
+    assert_eq!(u64_at(usage + 40), 1);
+    assert_eq!(data.len(), usage + 48);
+}
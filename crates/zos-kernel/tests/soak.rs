@@ -0,0 +1,690 @@
+//! Soak harness
+//!
+//! Runs the kernel "in a box" - a native [`System`] over an in-memory HAL,
+//! next to a [`MemoryVfs`] - and drives it with randomized workloads: process
+//! spawns and kills, endpoint creation, capability grants and revokes, IPC
+//! traffic and VFS writes/deletes against per-user quotas.
+//!
+//! A run is a sequence of *epochs*. Each epoch boots a fresh box from its own
+//! seed and executes [`OPS_PER_EPOCH`] operations, checking invariants every
+//! [`CHECK_INTERVAL`] operations and at the end:
+//!
+//! - **No leaked endpoints**: every endpoint is owned by a live process and
+//!   holds exactly the messages the workload left in it
+//! - **Axiom hash consistency**: the CommitLog hash chain verifies, and
+//!   replaying it into a fresh system reproduces the live state hash
+//! - **Quota math**: per-user VFS usage equals the bytes the workload wrote,
+//!   never exceeds the user's quota, and `remaining()` agrees with both
+//!
+//! On a violation the epoch is re-run with a check after every operation to
+//! find the shortest failing prefix, and the test panics with the seed and
+//! operation count needed to reproduce it.
+//!
+//! ```text
+//! # Short run (part of the normal test suite)
+//! cargo test -p zos-kernel --test soak
+//!
+//! # Long run, default 4 hours (override with ZOS_SOAK_SECS)
+//! ZOS_SOAK_SECS=14400 cargo test -p zos-kernel --release --test soak -- --ignored --nocapture
+//!
+//! # Reproduce a reported failure
+//! ZOS_SOAK_SEED=0x... ZOS_SOAK_OPS=1234 cargo test -p zos-kernel --test soak soak_reproduce -- --ignored
+//! ```
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use zos_hal::{HalError, NumericProcessHandle, HAL};
+use zos_kernel::{replay_and_verify, CapSlot, Permissions, ProcessId, Replayable, System};
+use zos_vfs::{MemoryVfs, StorageQuota, UserId, VfsService};
+
+/// Operations per epoch. Keeps the CommitLog well below its retention limit
+/// so the whole log can be replayed.
+const OPS_PER_EPOCH: u32 = 4096;
+
+/// Operations between invariant checks.
+const CHECK_INTERVAL: u32 = 128;
+
+/// Upper bound on live processes in the box.
+const MAX_PROCESSES: usize = 24;
+
+/// Users owning files in the box.
+const USERS: &[UserId] = &[1, 2, 3];
+
+/// Per-user quota, small enough that the workload regularly hits it.
+const USER_QUOTA_BYTES: u64 = 64 * 1024;
+
+/// Largest file the workload writes.
+const MAX_FILE_BYTES: u64 = 8 * 1024;
+
+/// Default length of the long run.
+const DEFAULT_SOAK_SECS: u64 = 4 * 60 * 60;
+
+// ============================================================================
+// In-box HAL
+// ============================================================================
+
+/// Native HAL for the box: a manually advanced clock and nothing else.
+///
+/// Processes are registered with the kernel directly, so the process and
+/// message hooks are never exercised.
+#[derive(Default)]
+struct SoakHal {
+    time: AtomicU64,
+}
+
+impl SoakHal {
+    fn advance(&self, nanos: u64) {
+        self.time.fetch_add(nanos, Ordering::SeqCst);
+    }
+}
+
+impl HAL for SoakHal {
+    type ProcessHandle = NumericProcessHandle;
+
+    fn spawn_process(&self, _name: &str, _binary: &[u8]) -> Result<Self::ProcessHandle, HalError> {
+        Err(HalError::NotSupported)
+    }
+
+    fn kill_process(&self, _handle: &Self::ProcessHandle) -> Result<(), HalError> {
+        Err(HalError::NotSupported)
+    }
+
+    fn send_to_process(&self, _handle: &Self::ProcessHandle, _msg: &[u8]) -> Result<(), HalError> {
+        Ok(())
+    }
+
+    fn is_process_alive(&self, _handle: &Self::ProcessHandle) -> bool {
+        true
+    }
+
+    fn get_process_memory_size(&self, _handle: &Self::ProcessHandle) -> Result<usize, HalError> {
+        Ok(65536)
+    }
+
+    fn allocate(&self, _size: usize, _align: usize) -> Result<*mut u8, HalError> {
+        Err(HalError::NotSupported)
+    }
+
+    unsafe fn deallocate(&self, _ptr: *mut u8, _size: usize, _align: usize) {}
+
+    fn now_nanos(&self) -> u64 {
+        self.time.load(Ordering::SeqCst)
+    }
+
+    fn wallclock_ms(&self) -> u64 {
+        1737504000000 + self.now_nanos() / 1_000_000
+    }
+
+    fn random_bytes(&self, buf: &mut [u8]) -> Result<(), HalError> {
+        buf.fill(0);
+        Ok(())
+    }
+
+    fn debug_write(&self, _msg: &str) {}
+
+    fn poll_messages(&self) -> Vec<(Self::ProcessHandle, Vec<u8>)> {
+        Vec::new()
+    }
+}
+
+// ============================================================================
+// Workload
+// ============================================================================
+
+/// SplitMix64, so a seed fully determines an epoch.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> Option<T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(items[self.below(items.len() as u64) as usize])
+        }
+    }
+}
+
+/// A freshly booted kernel and VFS plus the workload's model of what they
+/// should contain.
+struct KernelBox {
+    system: System<SoakHal>,
+    vfs: MemoryVfs,
+    rng: Rng,
+    /// Messages the workload left queued, by endpoint ID
+    queued: BTreeMap<u64, usize>,
+    /// Files the workload wrote, by path, with owner and size
+    files: BTreeMap<String, (UserId, u64)>,
+    next_file: u64,
+    /// Description of the last operation, for failure reports
+    last_op: String,
+}
+
+impl KernelBox {
+    fn boot(seed: u64) -> Self {
+        let mut system = System::new(SoakHal::default());
+        system.register_process("init");
+
+        let vfs = MemoryVfs::new();
+        for &user in USERS {
+            vfs.mkdir_p(&vfs.get_home_dir(user))
+                .expect("create home directory");
+            vfs.set_quota(user, USER_QUOTA_BYTES).expect("set quota");
+        }
+
+        Self {
+            system,
+            vfs,
+            rng: Rng(seed),
+            queued: BTreeMap::new(),
+            files: BTreeMap::new(),
+            next_file: 0,
+            last_op: String::from("boot"),
+        }
+    }
+
+    fn live_pids(&self) -> Vec<ProcessId> {
+        self.system
+            .list_processes()
+            .into_iter()
+            .map(|(pid, _)| pid)
+            .collect()
+    }
+
+    /// Endpoint capabilities held by `pid`, as (slot, endpoint ID).
+    fn endpoint_caps(&self, pid: ProcessId) -> Vec<(CapSlot, u64)> {
+        self.system
+            .get_cap_space(pid)
+            .map(|cspace| {
+                cspace
+                    .slots
+                    .iter()
+                    .filter(|(_, cap)| cap.object_type == zos_kernel::ObjectType::Endpoint)
+                    .map(|(slot, cap)| (*slot, cap.object_id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Execute one random operation.
+    fn step(&mut self) {
+        self.system.hal().advance(1_000_000);
+        match self.rng.below(100) {
+            0..=7 => self.op_spawn(),
+            8..=11 => self.op_kill(),
+            12..=21 => self.op_create_endpoint(),
+            22..=36 => self.op_grant(),
+            37..=44 => self.op_revoke(),
+            45..=62 => self.op_send(),
+            63..=74 => self.op_receive(),
+            75..=89 => self.op_write_file(),
+            _ => self.op_delete_file(),
+        }
+    }
+
+    fn op_spawn(&mut self) {
+        if self.system.list_processes().len() >= MAX_PROCESSES {
+            self.last_op = String::from("spawn (skipped, table full)");
+            return;
+        }
+        let name = format!("soak-{}", self.rng.below(1000));
+        let pid = self.system.register_process(&name);
+        self.last_op = format!("spawn {} -> PID {}", name, pid.0);
+    }
+
+    fn op_kill(&mut self) {
+        // Never kill init
+        let candidates: Vec<ProcessId> = self
+            .live_pids()
+            .into_iter()
+            .filter(|pid| pid.0 > 1)
+            .collect();
+        let Some(pid) = self.rng.pick(&candidates) else {
+            self.last_op = String::from("kill (no candidates)");
+            return;
+        };
+        let owned: Vec<u64> = self
+            .system
+            .list_endpoints()
+            .into_iter()
+            .filter(|ep| ep.owner == pid)
+            .map(|ep| ep.id.0)
+            .collect();
+        self.system.kill_process(pid);
+        for id in owned {
+            self.queued.remove(&id);
+        }
+        // The kernel reports the exit to init's first endpoint, if it has one
+        let init_endpoint = self
+            .system
+            .list_endpoints()
+            .into_iter()
+            .find(|ep| ep.owner == ProcessId(1))
+            .map(|ep| ep.id.0);
+        if let Some(id) = init_endpoint {
+            *self.queued.entry(id).or_default() += 1;
+        }
+        self.last_op = format!("kill PID {}", pid.0);
+    }
+
+    fn op_create_endpoint(&mut self) {
+        let pids = self.live_pids();
+        let Some(owner) = self.rng.pick(&pids) else {
+            return;
+        };
+        match self.system.create_endpoint(owner) {
+            Ok((id, slot)) => {
+                self.queued.insert(id.0, 0);
+                self.last_op = format!(
+                    "create endpoint {} for PID {} (slot {})",
+                    id.0, owner.0, slot
+                );
+            }
+            Err(e) => self.last_op = format!("create endpoint for PID {}: {:?}", owner.0, e),
+        }
+    }
+
+    fn op_grant(&mut self) {
+        let pids = self.live_pids();
+        let (Some(from), Some(to)) = (self.rng.pick(&pids), self.rng.pick(&pids)) else {
+            return;
+        };
+        let Some((slot, _)) = self.rng.pick(&self.endpoint_caps(from)) else {
+            self.last_op = format!("grant from PID {} (no caps)", from.0);
+            return;
+        };
+        let bits = self.rng.below(8);
+        let perms = Permissions {
+            read: bits & 1 != 0,
+            write: bits & 2 != 0,
+            grant: bits & 4 != 0,
+        };
+        let result = self.system.grant_capability(from, slot, to, perms);
+        self.last_op = format!(
+            "grant PID {} slot {} -> PID {} ({:?}): {:?}",
+            from.0, slot, to.0, perms, result
+        );
+    }
+
+    fn op_revoke(&mut self) {
+        let pids = self.live_pids();
+        let Some(pid) = self.rng.pick(&pids) else {
+            return;
+        };
+        let Some((slot, _)) = self.rng.pick(&self.endpoint_caps(pid)) else {
+            self.last_op = format!("revoke from PID {} (no caps)", pid.0);
+            return;
+        };
+        let result = self.system.revoke_capability(pid, slot);
+        self.last_op = format!("revoke PID {} slot {}: {:?}", pid.0, slot, result);
+    }
+
+    fn op_send(&mut self) {
+        let pids = self.live_pids();
+        let Some(from) = self.rng.pick(&pids) else {
+            return;
+        };
+        let Some((slot, endpoint)) = self.rng.pick(&self.endpoint_caps(from)) else {
+            self.last_op = format!("send from PID {} (no caps)", from.0);
+            return;
+        };
+        let len = self.rng.below(64) as usize;
+        let result = self
+            .system
+            .ipc_send(from, slot, 0x1000, alloc::vec![0xA5; len]);
+        if result.is_ok() {
+            *self.queued.entry(endpoint).or_insert(0) += 1;
+        }
+        self.last_op = format!(
+            "send PID {} slot {} (endpoint {}): {:?}",
+            from.0, slot, endpoint, result
+        );
+    }
+
+    fn op_receive(&mut self) {
+        let pids = self.live_pids();
+        let Some(pid) = self.rng.pick(&pids) else {
+            return;
+        };
+        let Some((slot, endpoint)) = self.rng.pick(&self.endpoint_caps(pid)) else {
+            self.last_op = format!("receive PID {} (no caps)", pid.0);
+            return;
+        };
+        let result = self.system.ipc_receive(pid, slot);
+        if let Ok(Some(_)) = result {
+            if let Some(count) = self.queued.get_mut(&endpoint) {
+                *count = count.saturating_sub(1);
+            }
+        }
+        self.last_op = format!(
+            "receive PID {} slot {} (endpoint {}): {}",
+            pid.0,
+            slot,
+            endpoint,
+            match result {
+                Ok(Some(_)) => String::from("message"),
+                Ok(None) => String::from("empty"),
+                Err(e) => format!("{:?}", e),
+            }
+        );
+    }
+
+    fn op_write_file(&mut self) {
+        let user = self.rng.pick(USERS).unwrap_or(1);
+        let owned: Vec<&String> = self
+            .files
+            .iter()
+            .filter(|(_, (owner, _))| *owner == user)
+            .map(|(path, _)| path)
+            .collect();
+
+        // Overwrite an existing file half the time
+        let path = match self.rng.below(2) {
+            0 if !owned.is_empty() => owned[self.rng.below(owned.len() as u64) as usize].clone(),
+            _ => {
+                self.next_file += 1;
+                format!("{}/f{}", self.vfs.get_home_dir(user), self.next_file)
+            }
+        };
+        let size = self.rng.below(MAX_FILE_BYTES + 1);
+        let old_size = self.files.get(&path).map(|(_, s)| *s).unwrap_or(0);
+
+        // The VFS service enforces quotas before writing; do the same here
+        let mut quota = self.vfs.get_quota(user).expect("get quota");
+        quota.used_bytes = self.used_bytes(user);
+        if size > old_size && quota.would_exceed(size - old_size) {
+            self.last_op = format!("write {} ({} bytes): over quota", path, size);
+            return;
+        }
+
+        match self.vfs.write_file(&path, &alloc::vec![0u8; size as usize]) {
+            Ok(()) => {
+                self.files.insert(path.clone(), (user, size));
+                self.last_op = format!("write {} ({} bytes)", path, size);
+            }
+            Err(e) => self.last_op = format!("write {}: {:?}", path, e),
+        }
+    }
+
+    fn op_delete_file(&mut self) {
+        let paths: Vec<&String> = self.files.keys().collect();
+        if paths.is_empty() {
+            self.last_op = String::from("delete (no files)");
+            return;
+        }
+        let path = paths[self.rng.below(paths.len() as u64) as usize].clone();
+        match self.vfs.unlink(&path) {
+            Ok(()) => {
+                self.files.remove(&path);
+                self.last_op = format!("delete {}", path);
+            }
+            Err(e) => self.last_op = format!("delete {}: {:?}", path, e),
+        }
+    }
+
+    /// Bytes the model says `user` has written.
+    fn used_bytes(&self, user: UserId) -> u64 {
+        self.files
+            .values()
+            .filter(|(owner, _)| *owner == user)
+            .map(|(_, size)| size)
+            .sum()
+    }
+
+    // ========================================================================
+    // Invariants
+    // ========================================================================
+
+    fn check_invariants(&self) -> Result<(), String> {
+        self.check_endpoints()?;
+        self.check_axiom()?;
+        self.check_quotas()
+    }
+
+    fn check_endpoints(&self) -> Result<(), String> {
+        let endpoints = self.system.list_endpoints();
+        for ep in &endpoints {
+            if self.system.get_process(ep.owner).is_none() {
+                return Err(format!(
+                    "endpoint {} leaked: owner PID {} is dead",
+                    ep.id.0, ep.owner.0
+                ));
+            }
+            match self.queued.get(&ep.id.0) {
+                None => return Err(format!("endpoint {} is unknown to the workload", ep.id.0)),
+                Some(&expected) if expected != ep.queue_depth => {
+                    return Err(format!(
+                        "endpoint {} holds {} messages, expected {}",
+                        ep.id.0, ep.queue_depth, expected
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+        if endpoints.len() != self.queued.len() {
+            return Err(format!(
+                "{} endpoints alive, workload expects {}",
+                endpoints.len(),
+                self.queued.len()
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_axiom(&self) -> Result<(), String> {
+        let log = self.system.commitlog();
+        if !log.verify_integrity() {
+            return Err(String::from("CommitLog hash chain is broken"));
+        }
+        if !self.system.verify_state_digest() {
+            return Err(String::from("incremental state digest drifted from state"));
+        }
+
+        let mut replayed = System::<SoakHal>::new_for_replay();
+        replay_and_verify(&mut replayed, log.commits(), self.system.state_hash())
+            .map_err(|e| format!("replay of {} commits diverged: {:?}", log.len(), e))
+    }
+
+    fn check_quotas(&self) -> Result<(), String> {
+        for &user in USERS {
+            let home = self.vfs.get_home_dir(user);
+            let usage = self
+                .vfs
+                .get_usage(&home)
+                .map_err(|e| format!("usage of {}: {:?}", home, e))?;
+            let expected = self.used_bytes(user);
+            if usage.used_bytes != expected {
+                return Err(format!(
+                    "user {} uses {} bytes, workload wrote {}",
+                    user, usage.used_bytes, expected
+                ));
+            }
+
+            let limit = self
+                .vfs
+                .get_quota(user)
+                .map_err(|e| format!("quota of user {}: {:?}", user, e))?;
+            let mut quota = StorageQuota::with_limit(user, limit.max_bytes);
+            quota.used_bytes = usage.used_bytes;
+            if quota.used_bytes > quota.max_bytes {
+                return Err(format!(
+                    "user {} over quota: {} > {}",
+                    user, quota.used_bytes, quota.max_bytes
+                ));
+            }
+            if quota.remaining() + quota.used_bytes != quota.max_bytes {
+                return Err(format!(
+                    "user {} quota math: remaining {} + used {} != max {}",
+                    user,
+                    quota.remaining(),
+                    quota.used_bytes,
+                    quota.max_bytes
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Runner
+// ============================================================================
+
+/// An invariant violation found by an epoch.
+#[derive(Debug)]
+struct Violation {
+    /// Epoch seed
+    seed: u64,
+    /// Operations executed when the violation was detected
+    ops: u32,
+    /// Last operation before detection
+    last_op: String,
+    /// Which invariant failed, and how
+    reason: String,
+}
+
+impl core::fmt::Display for Violation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "soak invariant violated after {} ops (seed {:#018x}): {}\n  last op: {}\n  reproduce: ZOS_SOAK_SEED={:#018x} ZOS_SOAK_OPS={} cargo test -p zos-kernel --test soak soak_reproduce -- --ignored",
+            self.ops, self.seed, self.reason, self.last_op, self.seed, self.ops
+        )
+    }
+}
+
+/// Run `ops` operations from `seed`, checking every `interval` operations
+/// and at the end.
+fn run_epoch(seed: u64, ops: u32, interval: u32) -> Result<(), Violation> {
+    let mut kbox = KernelBox::boot(seed);
+    for i in 1..=ops {
+        kbox.step();
+        if i % interval == 0 || i == ops {
+            kbox.check_invariants().map_err(|reason| Violation {
+                seed,
+                ops: i,
+                last_op: kbox.last_op.clone(),
+                reason,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Run an epoch; on failure, re-run it checking after every operation so the
+/// report names the shortest failing prefix.
+fn run_epoch_minimized(seed: u64) -> Result<(), Violation> {
+    match run_epoch(seed, OPS_PER_EPOCH, CHECK_INTERVAL) {
+        Ok(()) => Ok(()),
+        Err(found) => Err(run_epoch(seed, found.ops, 1).err().unwrap_or(found)),
+    }
+}
+
+/// Seed of the `index`th epoch of a run.
+fn epoch_seed(run_seed: u64, index: u64) -> u64 {
+    Rng(run_seed ^ index.wrapping_mul(0xA24B_AED4_963E_E407)).next()
+}
+
+/// Run epochs until `budget` elapses (at least `min_epochs`), returning the
+/// number of epochs run.
+fn soak(run_seed: u64, budget: Duration, min_epochs: u64) -> u64 {
+    let start = Instant::now();
+    let mut epochs = 0;
+    while epochs < min_epochs || start.elapsed() < budget {
+        let seed = epoch_seed(run_seed, epochs);
+        if let Err(violation) = run_epoch_minimized(seed) {
+            panic!("{}", violation);
+        }
+        epochs += 1;
+    }
+    epochs
+}
+
+fn env_u64(name: &str) -> Option<u64> {
+    let value = std::env::var(name).ok()?;
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn soak_short() {
+    let epochs = soak(0x5EED, Duration::ZERO, 8);
+    assert_eq!(epochs, 8);
+}
+
+#[test]
+fn soak_epoch_is_deterministic() {
+    let mut a = KernelBox::boot(42);
+    let mut b = KernelBox::boot(42);
+    for _ in 0..500 {
+        a.step();
+        b.step();
+        assert_eq!(a.last_op, b.last_op);
+    }
+    assert_eq!(a.system.state_hash(), b.system.state_hash());
+}
+
+#[test]
+fn soak_detects_endpoint_leak() {
+    let mut kbox = KernelBox::boot(7);
+    let pid = kbox.system.register_process("leaky");
+    // Create an endpoint behind the workload's back
+    kbox.system.create_endpoint(pid).unwrap();
+    let err = kbox.check_invariants().unwrap_err();
+    assert!(err.contains("unknown to the workload"), "{}", err);
+}
+
+/// Long-running soak. Runs for `ZOS_SOAK_SECS` (default 4 hours) from
+/// `ZOS_SOAK_SEED` (default: wall clock).
+#[test]
+#[ignore]
+fn soak_long() {
+    let secs = env_u64("ZOS_SOAK_SECS").unwrap_or(DEFAULT_SOAK_SECS);
+    let run_seed = env_u64("ZOS_SOAK_SEED").unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+    });
+    println!("soak: run seed {:#018x}, {}s budget", run_seed, secs);
+    let epochs = soak(run_seed, Duration::from_secs(secs), 1);
+    println!(
+        "soak: {} epochs ({} ops) without violations",
+        epochs,
+        epochs * OPS_PER_EPOCH as u64
+    );
+}
+
+/// Re-run a single epoch reported by a failing soak.
+#[test]
+#[ignore]
+fn soak_reproduce() {
+    let seed = env_u64("ZOS_SOAK_SEED").expect("set ZOS_SOAK_SEED to the reported seed");
+    let ops = env_u64("ZOS_SOAK_OPS").map_or(OPS_PER_EPOCH, |n| n as u32);
+    if let Err(violation) = run_epoch(seed, ops, 1) {
+        panic!("{}", violation);
+    }
+}
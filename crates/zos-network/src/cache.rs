@@ -0,0 +1,662 @@
+//! HTTP response cache
+//!
+//! The Network Service keeps a shared cache of GET responses. Bodies live in
+//! the VFS under [`CACHE_DIR`], one file per entry, and the metadata for all
+//! of them in a [`CacheIndex`] persisted at [`INDEX_PATH`].
+//!
+//! Entries are keyed by method, URL and the values of the request headers
+//! the response's `Vary` names, and follow the rules of a shared cache:
+//!
+//! - Responses with `Cache-Control: no-store` or `private`, `Vary: *`, or to
+//!   requests carrying `Authorization` (unless `public`) are not stored
+//! - A response is fresh for `s-maxage`, else `max-age`, less its `Age`;
+//!   `no-cache` makes it stale at once. `Expires` is not consulted
+//! - A stale entry with an `ETag` or `Last-Modified` is revalidated with
+//!   `If-None-Match`/`If-Modified-Since`; one without is refetched
+//! - Requests with `Cache-Control: no-store`, their own conditional or
+//!   `Range` headers, or a body bypass the cache; `no-cache` forces
+//!   revalidation
+//! - Any other method invalidates the entries for its URL
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::{HttpMethod, HttpRequest, HttpResponse, HttpSuccess};
+
+/// VFS directory holding cached bodies.
+pub const CACHE_DIR: &str = "/var/cache/net";
+
+/// VFS path of the persisted [`CacheIndex`].
+pub const INDEX_PATH: &str = "/var/cache/net/index.json";
+
+/// Maximum number of cached responses.
+pub const MAX_CACHE_ENTRIES: usize = 256;
+
+/// Maximum total size of cached bodies, in bytes.
+pub const MAX_CACHE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Largest body that will be cached, in bytes.
+pub const MAX_ENTRY_BYTES: usize = 1024 * 1024;
+
+/// Response headers that are never stored: cookies belong to the client
+/// that received them, and `Age` only means something on arrival.
+const UNSTORED_HEADERS: &[&str] = &["set-cookie", "set-cookie2", "age"];
+
+/// VFS path of the body of entry `id`.
+pub fn body_path(id: &str) -> String {
+    format!("{}/{}", CACHE_DIR, id)
+}
+
+// =============================================================================
+// Header helpers
+// =============================================================================
+
+/// Value of header `name`, with repeated headers joined by `", "`.
+pub fn header_value(headers: &[(String, String)], name: &str) -> Option<String> {
+    let values: Vec<&str> = headers
+        .iter()
+        .filter(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.trim())
+        .collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(", "))
+    }
+}
+
+/// Parsed `Cache-Control` directives.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    pub no_store: bool,
+    pub no_cache: bool,
+    pub private: bool,
+    pub public: bool,
+    pub max_age: Option<u64>,
+    pub s_maxage: Option<u64>,
+}
+
+impl CacheControl {
+    /// Parse the `Cache-Control` headers in `headers`.
+    ///
+    /// Unknown directives are ignored, and an unparseable `max-age` counts
+    /// as zero, so a malformed header errs towards revalidating.
+    pub fn from_headers(headers: &[(String, String)]) -> Self {
+        let mut cc = Self::default();
+        let Some(value) = header_value(headers, "cache-control") else {
+            return cc;
+        };
+        for directive in value.split(',') {
+            let (name, arg) = match directive.split_once('=') {
+                Some((n, a)) => (n.trim(), Some(a.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+            let seconds = || arg.and_then(|a| a.parse::<u64>().ok()).unwrap_or(0);
+            match name.to_ascii_lowercase().as_str() {
+                "no-store" => cc.no_store = true,
+                "no-cache" => cc.no_cache = true,
+                "private" => cc.private = true,
+                "public" => cc.public = true,
+                "max-age" => cc.max_age = Some(seconds()),
+                "s-maxage" => cc.s_maxage = Some(seconds()),
+                _ => {}
+            }
+        }
+        cc
+    }
+}
+
+// =============================================================================
+// Cache Entry
+// =============================================================================
+
+/// Metadata of one cached response.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// Request method
+    pub method: HttpMethod,
+    /// Request URL
+    pub url: String,
+    /// Request header values the response varies on (lowercase name, value)
+    pub vary: Vec<(String, String)>,
+    /// Response status
+    pub status: u16,
+    /// Response headers
+    pub headers: Vec<(String, String)>,
+    /// Body size in bytes
+    pub size: u64,
+    /// Wall-clock ms until which the entry may be served without revalidation
+    pub fresh_until_ms: u64,
+    /// Wall-clock ms the entry was last served or refreshed
+    pub last_used_ms: u64,
+}
+
+/// How the cache can answer a request.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CacheLookup<'a> {
+    /// The request must not use the cache
+    Bypass,
+    /// Nothing usable is cached
+    Miss,
+    /// Serve the cached entry as is
+    Fresh(&'a str, &'a CacheEntry),
+    /// Revalidate the cached entry with the origin
+    Revalidate(&'a str, &'a CacheEntry),
+}
+
+impl CacheEntry {
+    /// Build an entry for `response` to `request`, with its ID, or `None` if
+    /// the response may not be stored.
+    pub fn from_response(
+        request: &HttpRequest,
+        response: &HttpSuccess,
+        now_ms: u64,
+    ) -> Option<(String, CacheEntry)> {
+        if !is_cacheable_request(request) || response.status != 200 {
+            return None;
+        }
+        if response.body.len() > MAX_ENTRY_BYTES {
+            return None;
+        }
+        let cc = CacheControl::from_headers(&response.headers);
+        if cc.no_store || cc.private {
+            return None;
+        }
+        if header_value(&request.headers, "authorization").is_some() && !cc.public {
+            return None;
+        }
+
+        let mut vary = Vec::new();
+        if let Some(names) = header_value(&response.headers, "vary") {
+            for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                if name == "*" {
+                    return None;
+                }
+                let name = name.to_ascii_lowercase();
+                let value = header_value(&request.headers, &name).unwrap_or_default();
+                vary.push((name, value));
+            }
+            vary.sort();
+            vary.dedup();
+        }
+
+        let mut entry = CacheEntry {
+            method: request.method,
+            url: request.url.clone(),
+            vary,
+            status: response.status,
+            headers: Vec::new(),
+            size: response.body.len() as u64,
+            fresh_until_ms: 0,
+            last_used_ms: now_ms,
+        };
+        entry.update_headers(&response.headers, now_ms);
+        if entry.fresh_until_ms <= now_ms && !entry.has_validator() {
+            // Would never be servable
+            return None;
+        }
+        Some((entry.id(), entry))
+    }
+
+    /// Entry ID: a hash of the key, also naming the body file.
+    pub fn id(&self) -> String {
+        let mut hash = Fnv1a::new();
+        hash.write(self.method.as_str().as_bytes());
+        hash.write(self.url.as_bytes());
+        for (name, value) in &self.vary {
+            hash.write(name.as_bytes());
+            hash.write(value.as_bytes());
+        }
+        format!("{:016x}", hash.finish())
+    }
+
+    /// Whether the entry answers `request` (same method, URL and varied
+    /// header values).
+    pub fn matches(&self, request: &HttpRequest) -> bool {
+        self.method == request.method
+            && self.url == request.url
+            && self.vary.iter().all(|(name, value)| {
+                header_value(&request.headers, name).unwrap_or_default() == *value
+            })
+    }
+
+    /// Whether the entry carries an `ETag` or `Last-Modified` validator.
+    pub fn has_validator(&self) -> bool {
+        self.etag().is_some() || self.last_modified().is_some()
+    }
+
+    fn etag(&self) -> Option<String> {
+        header_value(&self.headers, "etag")
+    }
+
+    fn last_modified(&self) -> Option<String> {
+        header_value(&self.headers, "last-modified")
+    }
+
+    /// `request` with conditional headers asking the origin whether this
+    /// entry is still current.
+    pub fn conditional_request(&self, request: &HttpRequest) -> HttpRequest {
+        let mut request = request.clone();
+        if let Some(etag) = self.etag() {
+            request.headers.push(("If-None-Match".into(), etag));
+        }
+        if let Some(date) = self.last_modified() {
+            request.headers.push(("If-Modified-Since".into(), date));
+        }
+        request
+    }
+
+    /// Apply a `304 Not Modified` from revalidation: its headers replace the
+    /// stored ones of the same name and the freshness lifetime restarts.
+    pub fn refresh(&mut self, not_modified: &HttpSuccess, now_ms: u64) {
+        let mut headers = self.headers.clone();
+        headers.retain(|(k, _)| {
+            !not_modified
+                .headers
+                .iter()
+                .any(|(n, _)| n.eq_ignore_ascii_case(k))
+        });
+        headers.extend(not_modified.headers.iter().cloned());
+        self.update_headers(&headers, now_ms);
+        self.last_used_ms = now_ms;
+    }
+
+    /// Store `headers` and recompute freshness from them.
+    fn update_headers(&mut self, headers: &[(String, String)], now_ms: u64) {
+        self.headers = headers
+            .iter()
+            .filter(|(k, _)| {
+                !UNSTORED_HEADERS
+                    .iter()
+                    .any(|name| k.eq_ignore_ascii_case(name))
+            })
+            .cloned()
+            .collect();
+
+        let cc = CacheControl::from_headers(headers);
+        let lifetime = if cc.no_cache {
+            0
+        } else {
+            cc.s_maxage.or(cc.max_age).unwrap_or(0)
+        };
+        let age = header_value(headers, "age")
+            .and_then(|a| a.parse::<u64>().ok())
+            .unwrap_or(0);
+        self.fresh_until_ms =
+            now_ms.saturating_add(lifetime.saturating_sub(age).saturating_mul(1000));
+    }
+
+    /// The cached response with `body`.
+    pub fn response(&self, body: Vec<u8>) -> HttpResponse {
+        HttpResponse::ok(self.status, self.headers.clone(), body)
+    }
+}
+
+/// Whether `request` may be answered from or stored in the cache.
+fn is_cacheable_request(request: &HttpRequest) -> bool {
+    if request.method != HttpMethod::Get || request.body.is_some() {
+        return false;
+    }
+    if CacheControl::from_headers(&request.headers).no_store {
+        return false;
+    }
+    // The client is managing validation or ranges itself
+    ["if-none-match", "if-modified-since", "range"]
+        .iter()
+        .all(|name| header_value(&request.headers, name).is_none())
+}
+
+/// Whether `request` asks for revalidation even of a fresh entry.
+fn forces_revalidation(request: &HttpRequest) -> bool {
+    CacheControl::from_headers(&request.headers).no_cache
+        || header_value(&request.headers, "pragma")
+            .is_some_and(|p| p.eq_ignore_ascii_case("no-cache"))
+}
+
+/// Whether `method` changes the resource, making cached responses to its
+/// URL stale.
+pub fn invalidates(method: HttpMethod) -> bool {
+    !matches!(
+        method,
+        HttpMethod::Get | HttpMethod::Head | HttpMethod::Options
+    )
+}
+
+// =============================================================================
+// Cache Index
+// =============================================================================
+
+/// Metadata of every cached response, by entry ID.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheIndex {
+    #[serde(default)]
+    pub entries: BTreeMap<String, CacheEntry>,
+}
+
+impl CacheIndex {
+    /// Find how the cache can answer `request` at `now_ms`.
+    pub fn lookup(&self, request: &HttpRequest, now_ms: u64) -> CacheLookup<'_> {
+        if !is_cacheable_request(request) {
+            return CacheLookup::Bypass;
+        }
+        let Some((id, entry)) = self.entries.iter().find(|(_, e)| e.matches(request)) else {
+            return CacheLookup::Miss;
+        };
+        if now_ms < entry.fresh_until_ms && !forces_revalidation(request) {
+            CacheLookup::Fresh(id, entry)
+        } else if entry.has_validator() {
+            CacheLookup::Revalidate(id, entry)
+        } else {
+            CacheLookup::Miss
+        }
+    }
+
+    /// Total size of cached bodies.
+    pub fn total_bytes(&self) -> u64 {
+        self.entries.values().map(|e| e.size).sum()
+    }
+
+    /// Mark entry `id` as used.
+    pub fn touch(&mut self, id: &str, now_ms: u64) {
+        if let Some(entry) = self.entries.get_mut(id) {
+            entry.last_used_ms = now_ms;
+        }
+    }
+
+    /// Add or replace entry `id`, evicting the least recently used entries
+    /// to stay within [`MAX_CACHE_ENTRIES`] and [`MAX_CACHE_BYTES`].
+    ///
+    /// Returns the IDs of evicted entries, whose bodies must be deleted.
+    pub fn insert(&mut self, id: String, entry: CacheEntry) -> Vec<String> {
+        // Older variants with the same vary values under another ID can't
+        // exist: the ID is derived from them
+        self.entries.insert(id.clone(), entry);
+
+        let mut evicted = Vec::new();
+        let mut total = self.total_bytes();
+        while self.entries.len() > MAX_CACHE_ENTRIES || total > MAX_CACHE_BYTES {
+            let Some(victim) = self
+                .entries
+                .iter()
+                .filter(|(k, _)| **k != id)
+                .min_by_key(|(_, e)| e.last_used_ms)
+                .map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            if let Some(e) = self.entries.remove(&victim) {
+                total -= e.size;
+            }
+            evicted.push(victim);
+        }
+        evicted
+    }
+
+    /// Remove every entry for `url`, returning their IDs.
+    pub fn remove_url(&mut self, url: &str) -> Vec<String> {
+        let ids: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| e.url == url)
+            .map(|(k, _)| k.clone())
+            .collect();
+        for id in &ids {
+            self.entries.remove(id);
+        }
+        ids
+    }
+
+    /// Remove every entry, returning their IDs.
+    pub fn clear(&mut self) -> Vec<String> {
+        let ids = self.entries.keys().cloned().collect();
+        self.entries.clear();
+        ids
+    }
+
+    /// Serialize to JSON bytes.
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes.
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+}
+
+/// Cache counters, as reported by the Network Service.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Responses cached
+    pub entries: u64,
+    /// Total size of cached bodies
+    pub bytes: u64,
+    /// Requests served from the cache without contacting the origin
+    pub hits: u64,
+    /// Cacheable requests sent to the origin
+    pub misses: u64,
+    /// Revalidations answered with 304 Not Modified
+    pub revalidated: u64,
+    /// Responses written to the cache
+    pub stored: u64,
+    /// Entries evicted to stay within the size limits
+    pub evicted: u64,
+}
+
+// =============================================================================
+// Hashing
+// =============================================================================
+
+/// 64-bit FNV-1a, with a separator after each field.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes.iter().chain(core::iter::once(&0u8)) {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const NOW: u64 = 1_000_000;
+
+    fn ok(headers: &[(&str, &str)], body: &[u8]) -> HttpSuccess {
+        HttpSuccess {
+            status: 200,
+            headers: headers
+                .iter()
+                .map(|(k, v)| (String::from(*k), String::from(*v)))
+                .collect(),
+            body: body.to_vec(),
+        }
+    }
+
+    fn store(index: &mut CacheIndex, request: &HttpRequest, response: &HttpSuccess) -> String {
+        let (id, entry) = CacheEntry::from_response(request, response, NOW).unwrap();
+        assert!(index.insert(id.clone(), entry).is_empty());
+        id
+    }
+
+    #[test]
+    fn test_cache_control_parsing() {
+        let cc = CacheControl::from_headers(&[
+            ("Cache-Control".into(), "public, max-age=\"60\"".into()),
+            ("cache-control".into(), "no-cache, s-maxage=bogus".into()),
+        ]);
+        assert!(cc.public && cc.no_cache && !cc.no_store);
+        assert_eq!(cc.max_age, Some(60));
+        assert_eq!(cc.s_maxage, Some(0));
+    }
+
+    #[test]
+    fn test_freshness_and_revalidation() {
+        let mut index = CacheIndex::default();
+        let request = HttpRequest::get("https://example.com/a");
+        let response = ok(
+            &[
+                ("Cache-Control", "max-age=60"),
+                ("Age", "10"),
+                ("ETag", "\"v1\""),
+            ],
+            b"hello",
+        );
+        store(&mut index, &request, &response);
+
+        assert!(matches!(
+            index.lookup(&request, NOW + 49_000),
+            CacheLookup::Fresh(..)
+        ));
+        let CacheLookup::Revalidate(id, entry) = index.lookup(&request, NOW + 50_000) else {
+            panic!("expected revalidation once stale");
+        };
+        let conditional = entry.conditional_request(&request);
+        assert_eq!(
+            header_value(&conditional.headers, "if-none-match").as_deref(),
+            Some("\"v1\"")
+        );
+
+        // A 304 restarts the lifetime with its own headers
+        let id = String::from(id);
+        let not_modified = HttpSuccess {
+            status: 304,
+            headers: vec![("cache-control".into(), "max-age=120".into())],
+            body: Vec::new(),
+        };
+        let later = NOW + 50_000;
+        index
+            .entries
+            .get_mut(&id)
+            .unwrap()
+            .refresh(&not_modified, later);
+        assert!(matches!(
+            index.lookup(&request, later + 119_000),
+            CacheLookup::Fresh(..)
+        ));
+
+        // The client can force revalidation
+        let forced = request.clone().with_header("Cache-Control", "no-cache");
+        assert!(matches!(
+            index.lookup(&forced, later),
+            CacheLookup::Revalidate(..)
+        ));
+    }
+
+    #[test]
+    fn test_unstorable_responses() {
+        let request = HttpRequest::get("https://example.com/a");
+        for headers in [
+            &[("Cache-Control", "no-store, max-age=60")][..],
+            &[("Cache-Control", "private, max-age=60")],
+            &[("Cache-Control", "max-age=60"), ("Vary", "*")],
+            // No lifetime and nothing to revalidate with
+            &[("Content-Type", "text/plain")],
+        ] {
+            assert!(CacheEntry::from_response(&request, &ok(headers, b"x"), NOW).is_none());
+        }
+
+        let authed = request.clone().with_bearer_token("t");
+        let response = ok(&[("Cache-Control", "max-age=60")], b"x");
+        assert!(CacheEntry::from_response(&authed, &response, NOW).is_none());
+        let public = ok(&[("Cache-Control", "public, max-age=60")], b"x");
+        assert!(CacheEntry::from_response(&authed, &public, NOW).is_some());
+
+        let post = HttpRequest::post("https://example.com/a");
+        assert!(CacheEntry::from_response(&post, &response, NOW).is_none());
+
+        let big = ok(
+            &[("Cache-Control", "max-age=60")],
+            &vec![0; MAX_ENTRY_BYTES + 1],
+        );
+        assert!(CacheEntry::from_response(&request, &big, NOW).is_none());
+    }
+
+    #[test]
+    fn test_vary_keys_variants() {
+        let mut index = CacheIndex::default();
+        let response = ok(
+            &[("Cache-Control", "max-age=60"), ("Vary", "Accept-Language")],
+            b"x",
+        );
+        let en = HttpRequest::get("https://example.com/").with_header("Accept-Language", "en");
+        let fr = HttpRequest::get("https://example.com/").with_header("Accept-Language", "fr");
+        let en_id = store(&mut index, &en, &response);
+        assert_eq!(index.lookup(&fr, NOW), CacheLookup::Miss);
+        let fr_id = store(&mut index, &fr, &response);
+        assert_ne!(en_id, fr_id);
+
+        let CacheLookup::Fresh(id, _) = index.lookup(&en, NOW) else {
+            panic!("expected a hit");
+        };
+        assert_eq!(id, en_id);
+        assert_eq!(index.remove_url("https://example.com/").len(), 2);
+    }
+
+    #[test]
+    fn test_bypass_and_stored_headers() {
+        let index = CacheIndex::default();
+        let request = HttpRequest::get("https://example.com/");
+        for bypass in [
+            request.clone().with_header("Cache-Control", "no-store"),
+            request.clone().with_header("If-None-Match", "\"v1\""),
+            request.clone().with_header("Range", "bytes=0-10"),
+            request.clone().with_body(b"x".to_vec()),
+        ] {
+            assert_eq!(index.lookup(&bypass, NOW), CacheLookup::Bypass);
+        }
+
+        let response = ok(
+            &[("Cache-Control", "max-age=60"), ("Set-Cookie", "a=b")],
+            b"x",
+        );
+        let (_, entry) = CacheEntry::from_response(&request, &response, NOW).unwrap();
+        assert!(header_value(&entry.headers, "set-cookie").is_none());
+
+        assert!(invalidates(HttpMethod::Post));
+        assert!(!invalidates(HttpMethod::Head));
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut index = CacheIndex::default();
+        let body = vec![0u8; MAX_ENTRY_BYTES];
+        let response = ok(&[("Cache-Control", "max-age=60")], &body);
+        let per_entry = MAX_ENTRY_BYTES as u64;
+        let fit = (MAX_CACHE_BYTES / per_entry) as usize;
+
+        let mut ids = Vec::new();
+        for i in 0..fit {
+            let request = HttpRequest::get(format!("https://example.com/{}", i));
+            let (id, mut entry) = CacheEntry::from_response(&request, &response, NOW).unwrap();
+            entry.last_used_ms = NOW + i as u64;
+            assert!(index.insert(id.clone(), entry).is_empty());
+            ids.push(id);
+        }
+        // Using the oldest entry spares it
+        index.touch(&ids[0], NOW + 1_000);
+
+        let request = HttpRequest::get("https://example.com/new");
+        let (id, entry) = CacheEntry::from_response(&request, &response, NOW + 2_000).unwrap();
+        assert_eq!(index.insert(id, entry), vec![ids[1].clone()]);
+        assert_eq!(index.total_bytes(), MAX_CACHE_BYTES);
+
+        let index = CacheIndex::from_json(&index.to_json()).unwrap();
+        assert_eq!(index.entries.len(), fit);
+    }
+}
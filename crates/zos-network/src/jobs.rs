@@ -0,0 +1,302 @@
+//! Durable network jobs
+//!
+//! A request with [`HttpRequest::durable`] set is recorded in a
+//! [`JobJournal`] at [`JOURNAL_PATH`] before it is sent, so a page reload
+//! mid-transfer doesn't lose it. Jobs belong to the *name* of the process
+//! that submitted them, since PIDs don't survive a reload.
+//!
+//! A job that completes before any reload is answered like any other
+//! request and dropped. After a reload the Network Service runs
+//! [`JobJournal::restore`]:
+//!
+//! - Interrupted requests with an idempotent method are sent again, up to
+//!   [`MAX_ATTEMPTS`] times in all
+//! - Interrupted POST and PATCH requests fail, as the origin may already
+//!   have acted on them
+//!
+//! Their responses are written to [`result_path`] and delivered as
+//! MSG_NET_JOB_RESULT whenever a process with the owner's name is running,
+//! until it acknowledges them with MSG_NET_JOB_ACK.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::{HttpMethod, HttpRequest, HttpResponse, NetworkError};
+
+/// VFS directory holding the journal and job results.
+pub const JOBS_DIR: &str = "/var/lib/net/jobs";
+
+/// VFS path of the persisted [`JobJournal`].
+pub const JOURNAL_PATH: &str = "/var/lib/net/jobs/journal.json";
+
+/// Maximum number of jobs, pending or awaiting acknowledgement.
+pub const MAX_JOBS: usize = 32;
+
+/// Maximum jobs per owner, so one app can't use up the journal.
+pub const MAX_JOBS_PER_OWNER: usize = 8;
+
+/// Times a job is sent before it is given up on.
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// Longest job name, in bytes.
+pub const MAX_NAME_LEN: usize = 128;
+
+/// VFS path of the response to job `id`.
+pub fn result_path(id: u32) -> String {
+    format!("{}/{}.json", JOBS_DIR, id)
+}
+
+/// Whether a request with `method` can safely be sent again after it may
+/// already have reached the origin.
+pub fn is_idempotent(method: HttpMethod) -> bool {
+    !matches!(method, HttpMethod::Post | HttpMethod::Patch)
+}
+
+// =============================================================================
+// Jobs
+// =============================================================================
+
+/// Progress of a durable job.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobState {
+    /// Not yet answered
+    Pending,
+    /// Answered; the response is at [`result_path`]
+    Done,
+    /// Failed without a response
+    Failed(NetworkError),
+}
+
+/// A journaled request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DurableJob {
+    /// Journal-assigned ID
+    pub id: u32,
+    /// Name of the process the job belongs to
+    pub owner: String,
+    /// Name the owner gave the job
+    pub name: String,
+    /// The request, without its `durable` marker
+    pub request: HttpRequest,
+    /// Progress
+    pub state: JobState,
+    /// Times the request has been sent
+    pub attempts: u32,
+}
+
+/// Every durable job, pending or awaiting acknowledgement.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct JobJournal {
+    /// ID of the next job
+    #[serde(default)]
+    pub next_id: u32,
+    /// Jobs in submission order
+    #[serde(default)]
+    pub jobs: Vec<DurableJob>,
+}
+
+impl JobJournal {
+    /// Record durable `request` for `owner` as sent once, returning its ID.
+    ///
+    /// Fails if the name is invalid, `owner` already has a job by that name
+    /// (whose result is still to come), or the journal is full.
+    pub fn add(&mut self, owner: &str, mut request: HttpRequest) -> Result<u32, NetworkError> {
+        let name = request.durable.take().unwrap_or_default();
+        if name.is_empty() || name.len() > MAX_NAME_LEN {
+            return Err(NetworkError::Other("Invalid durable job name".into()));
+        }
+        if self.find(owner, &name).is_some() {
+            return Err(NetworkError::Other(format!(
+                "Durable job '{}' already exists",
+                name
+            )));
+        }
+        let owned = self.jobs.iter().filter(|j| j.owner == owner).count();
+        if self.jobs.len() >= MAX_JOBS || owned >= MAX_JOBS_PER_OWNER {
+            return Err(NetworkError::Other(
+                "Service busy: durable job limit reached".into(),
+            ));
+        }
+
+        let id = self.next_id.max(1);
+        self.next_id = id.wrapping_add(1);
+        self.jobs.push(DurableJob {
+            id,
+            owner: owner.into(),
+            name,
+            request,
+            state: JobState::Pending,
+            attempts: 1,
+        });
+        Ok(id)
+    }
+
+    /// Job `id`.
+    pub fn get(&self, id: u32) -> Option<&DurableJob> {
+        self.jobs.iter().find(|j| j.id == id)
+    }
+
+    /// Job `id`, mutably.
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut DurableJob> {
+        self.jobs.iter_mut().find(|j| j.id == id)
+    }
+
+    /// `owner`'s job called `name`.
+    pub fn find(&self, owner: &str, name: &str) -> Option<&DurableJob> {
+        self.jobs
+            .iter()
+            .find(|j| j.owner == owner && j.name == name)
+    }
+
+    /// Remove job `id`.
+    pub fn remove(&mut self, id: u32) -> Option<DurableJob> {
+        let index = self.jobs.iter().position(|j| j.id == id)?;
+        Some(self.jobs.remove(index))
+    }
+
+    /// Settle the jobs a reload interrupted, returning the IDs of those to
+    /// send again. Their attempts are counted here, so the journal must be
+    /// persisted before they are sent.
+    pub fn restore(&mut self) -> Vec<u32> {
+        let mut resend = Vec::new();
+        for job in &mut self.jobs {
+            if job.state != JobState::Pending {
+                continue;
+            }
+            if !is_idempotent(job.request.method) {
+                job.state = JobState::Failed(NetworkError::Other(format!(
+                    "{} interrupted by a reload and not retried",
+                    job.request.method.as_str()
+                )));
+            } else if job.attempts >= MAX_ATTEMPTS {
+                job.state = JobState::Failed(NetworkError::Other(format!(
+                    "Gave up after {} attempts",
+                    job.attempts
+                )));
+            } else {
+                job.attempts += 1;
+                resend.push(job.id);
+            }
+        }
+        resend
+    }
+
+    /// Serialize to JSON bytes.
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes.
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+}
+
+// =============================================================================
+// IPC Payloads
+// =============================================================================
+
+/// Payload of MSG_NET_JOB_RESULT.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobResult {
+    /// Job name
+    pub job: String,
+    /// The response
+    pub response: HttpResponse,
+}
+
+/// Payload of MSG_NET_JOB_ACK.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobAck {
+    /// Job name
+    pub job: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_validates_and_limits() {
+        let mut journal = JobJournal::default();
+        let request = HttpRequest::get("https://example.com/a").durable("manifest");
+        let id = journal.add("update", request.clone()).unwrap();
+        assert_eq!(id, 1);
+        assert!(journal.get(id).unwrap().request.durable.is_none());
+
+        // Same name, same owner
+        assert!(journal.add("update", request.clone()).is_err());
+        // Another owner may use it
+        assert!(journal.add("downloads", request).is_ok());
+        assert!(journal
+            .add("update", HttpRequest::get("https://example.com/"))
+            .is_err());
+
+        for i in 1..MAX_JOBS_PER_OWNER {
+            let request = HttpRequest::get("https://example.com/").durable(format!("j{}", i));
+            journal.add("update", request).unwrap();
+        }
+        let request = HttpRequest::get("https://example.com/").durable("one-more");
+        assert!(journal.add("update", request).is_err());
+    }
+
+    #[test]
+    fn test_restore_resends_only_idempotent() {
+        let mut journal = JobJournal::default();
+        let get = journal
+            .add(
+                "update",
+                HttpRequest::get("https://example.com/a").durable("a"),
+            )
+            .unwrap();
+        let post = journal
+            .add(
+                "update",
+                HttpRequest::post("https://example.com/b").durable("b"),
+            )
+            .unwrap();
+        let done = journal
+            .add(
+                "update",
+                HttpRequest::get("https://example.com/c").durable("c"),
+            )
+            .unwrap();
+        journal.get_mut(done).unwrap().state = JobState::Done;
+
+        assert_eq!(journal.restore(), alloc::vec![get]);
+        assert_eq!(journal.get(get).unwrap().attempts, 2);
+        assert!(matches!(
+            journal.get(post).unwrap().state,
+            JobState::Failed(_)
+        ));
+        assert_eq!(journal.get(done).unwrap().state, JobState::Done);
+
+        // Gives up once out of attempts
+        assert_eq!(journal.restore(), alloc::vec![get]);
+        assert!(journal.restore().is_empty());
+        assert!(matches!(
+            journal.get(get).unwrap().state,
+            JobState::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn test_journal_round_trip() {
+        let mut journal = JobJournal::default();
+        let id = journal
+            .add(
+                "update",
+                HttpRequest::get("https://example.com/a").durable("a"),
+            )
+            .unwrap();
+        let mut parsed = JobJournal::from_json(&journal.to_json()).unwrap();
+        assert_eq!(parsed.next_id, journal.next_id);
+        assert_eq!(parsed.find("update", "a").unwrap().id, id);
+        assert!(parsed.remove(id).is_some());
+        assert!(parsed.get(id).is_none());
+        assert!(JobJournal::from_json(b"not json").is_none());
+        assert_eq!(result_path(id), "/var/lib/net/jobs/1.json");
+    }
+}
@@ -41,6 +41,13 @@
 //! │ Network Service │  ◄── Routes response to caller
 //! └─────────────────┘
 //! ```
+//!
+//! The Network Service refuses requests its [`policy::NetworkPolicy`] does
+//! not allow before they reach the supervisor, and answers what it can from
+//! its [`cache`] of earlier responses. Requests marked [`durable`] outlive a
+//! page reload as [`jobs`].
+//!
+//! [`durable`]: HttpRequest::durable
 
 #![no_std]
 
@@ -50,6 +57,10 @@ use alloc::string::String;
 use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
+pub mod cache;
+pub mod jobs;
+pub mod policy;
+
 // =============================================================================
 // HTTP Method
 // =============================================================================
@@ -111,6 +122,9 @@ pub struct HttpRequest {
     /// Request ID for tracking async response
     #[serde(default)]
     pub request_id: u32,
+    /// Name of the durable job this request runs as, if any (see [`jobs`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub durable: Option<String>,
 }
 
 impl HttpRequest {
@@ -124,6 +138,7 @@ impl HttpRequest {
             timeout_ms: 30_000,
             caller_pid: 0,
             request_id: 0,
+            durable: None,
         }
     }
 
@@ -137,6 +152,7 @@ impl HttpRequest {
             timeout_ms: 30_000,
             caller_pid: 0,
             request_id: 0,
+            durable: None,
         }
     }
 
@@ -170,6 +186,12 @@ impl HttpRequest {
     pub fn with_bearer_token(self, token: impl Into<String>) -> Self {
         self.with_header("Authorization", alloc::format!("Bearer {}", token.into()))
     }
+
+    /// Run as durable job `name`, completing even if the page reloads.
+    pub fn durable(mut self, name: impl Into<String>) -> Self {
+        self.durable = Some(name.into());
+        self
+    }
 }
 
 // =============================================================================
@@ -241,6 +263,8 @@ pub enum NetworkError {
     TlsError,
     /// Network service not available
     ServiceUnavailable,
+    /// The system is in offline mode
+    Offline,
     /// Other error with description
     Other(String),
 }
@@ -256,6 +280,7 @@ impl NetworkError {
             NetworkError::DnsError => "DNS resolution failed",
             NetworkError::TlsError => "SSL/TLS error",
             NetworkError::ServiceUnavailable => "Network service unavailable",
+            NetworkError::Offline => "Network is offline",
             NetworkError::Other(msg) => msg,
         }
     }
@@ -0,0 +1,328 @@
+//! Network access policy
+//!
+//! The Network Service checks every request it mediates against a single
+//! system-wide [`NetworkPolicy`], stored at [`POLICY_PATH`]:
+//!
+//! 1. **Offline** - while set, every request fails with
+//!    [`NetworkError::Offline`] before anything else is looked at
+//! 2. **Deny list** - a request to a matching origin fails with
+//!    [`NetworkError::PolicyDenied`]
+//! 3. **Allow list** - when non-empty, only matching origins are reachable
+//!
+//! # Rules
+//!
+//! A rule is an origin in canonical form (`https://api.example.com`,
+//! `http://localhost:8080`), or one whose host starts with `*.` to match
+//! any subdomain (`https://*.example.com`, which does not match
+//! `https://example.com` itself). Origins are lowercase and leave out the
+//! scheme's default port.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::CacheStats;
+use crate::NetworkError;
+
+/// VFS path of the persisted policy.
+pub const POLICY_PATH: &str = "/system/config/network.json";
+
+/// Maximum rules in each of the allow and deny lists.
+pub const MAX_RULES: usize = 64;
+
+/// System-wide network policy.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkPolicy {
+    /// Fail every request with [`NetworkError::Offline`]
+    #[serde(default)]
+    pub offline: bool,
+    /// Origins requests may go to; empty allows all that aren't denied
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Origins requests may never go to
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl NetworkPolicy {
+    /// Check a request URL, returning its origin if the request may proceed.
+    pub fn check(&self, url: &str) -> Result<String, NetworkError> {
+        if self.offline {
+            return Err(NetworkError::Offline);
+        }
+        let origin = origin_of(url).ok_or(NetworkError::InvalidUrl)?;
+        if self.deny.iter().any(|rule| rule_matches(rule, &origin)) {
+            return Err(NetworkError::PolicyDenied);
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|rule| rule_matches(rule, &origin)) {
+            return Err(NetworkError::PolicyDenied);
+        }
+        Ok(origin)
+    }
+
+    /// Check every rule is a canonical origin or subdomain pattern.
+    pub fn validate(&self) -> Result<(), String> {
+        for (list, rules) in [("allow", &self.allow), ("deny", &self.deny)] {
+            if rules.len() > MAX_RULES {
+                return Err(format!(
+                    "Too many {} rules: {} (max {})",
+                    list,
+                    rules.len(),
+                    MAX_RULES
+                ));
+            }
+            if let Some(rule) = rules.iter().find(|rule| !is_valid_rule(rule)) {
+                return Err(format!(
+                    "Invalid {} rule {:?}: expected an origin such as \"https://example.com\" or \"https://*.example.com\"",
+                    list, rule
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize to JSON bytes.
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes.
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+}
+
+/// Origin (`scheme://host[:port]`) of an http or https URL, in canonical
+/// form. `None` if the URL has no usable origin.
+pub fn origin_of(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let scheme = scheme.to_ascii_lowercase();
+    let default_port = match scheme.as_str() {
+        "http" => "80",
+        "https" => "443",
+        _ => return None,
+    };
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    // Credentials are not part of the origin
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let (host, port) = match host_port.strip_prefix('[') {
+        // IPv6 literal: "[::1]:8080"
+        Some(v6) => {
+            let (addr, after) = v6.split_once(']')?;
+            let port = match after {
+                "" => None,
+                _ => Some(after.strip_prefix(':')?),
+            };
+            (format!("[{}]", addr), port)
+        }
+        None => match host_port.split_once(':') {
+            Some((host, port)) => (String::from(host), Some(port)),
+            None => (String::from(host_port), None),
+        },
+    };
+    if host.is_empty() || host == "[]" {
+        return None;
+    }
+    if let Some(port) = port {
+        if port.is_empty() || port.parse::<u16>().is_err() {
+            return None;
+        }
+    }
+
+    let host = host.to_ascii_lowercase();
+    Some(match port {
+        Some(port) if port != default_port => format!("{}://{}:{}", scheme, host, port),
+        _ => format!("{}://{}", scheme, host),
+    })
+}
+
+/// Whether `rule` matches the canonical `origin`.
+fn rule_matches(rule: &str, origin: &str) -> bool {
+    let Some((scheme, suffix)) = rule.split_once("://*.") else {
+        return rule == origin;
+    };
+    let Some(host) = origin
+        .strip_prefix(scheme)
+        .and_then(|rest| rest.strip_prefix("://"))
+    else {
+        return false;
+    };
+    host.strip_suffix(suffix)
+        .is_some_and(|label| label.len() > 1 && label.ends_with('.'))
+}
+
+fn is_valid_rule(rule: &str) -> bool {
+    let canonical = match rule.split_once("://*.") {
+        Some((scheme, suffix)) => format!("{}://{}", scheme, suffix),
+        None => String::from(rule),
+    };
+    origin_of(&canonical).is_some_and(|origin| origin == canonical)
+}
+
+// =============================================================================
+// Traffic Accounting
+// =============================================================================
+
+/// Network traffic of one process, as counted by the Network Service.
+///
+/// Bytes are the request and response payloads exchanged with the service,
+/// not what went over the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppTraffic {
+    /// Requests forwarded to the network
+    pub requests: u64,
+    /// Requests refused by policy (including offline)
+    pub denied: u64,
+    /// Request payload bytes forwarded
+    pub bytes_sent: u64,
+    /// Response payload bytes delivered
+    pub bytes_received: u64,
+}
+
+/// Snapshot of the service's counters.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkStats {
+    /// Whether the policy is currently offline
+    pub offline: bool,
+    /// Traffic per process
+    pub apps: BTreeMap<u32, AppTraffic>,
+    /// Response cache counters
+    #[serde(default)]
+    pub cache: CacheStats,
+}
+
+impl NetworkStats {
+    /// Serialize to JSON bytes.
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes.
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_origin_of() {
+        assert_eq!(
+            origin_of("https://API.Example.com/v1?q=1").as_deref(),
+            Some("https://api.example.com")
+        );
+        assert_eq!(
+            origin_of("http://user:pw@localhost:8080").as_deref(),
+            Some("http://localhost:8080")
+        );
+        assert_eq!(
+            origin_of("https://example.com:443/").as_deref(),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            origin_of("http://[::1]:9999/rpc").as_deref(),
+            Some("http://[::1]:9999")
+        );
+        assert_eq!(origin_of("ftp://example.com"), None);
+        assert_eq!(origin_of("https://"), None);
+        assert_eq!(origin_of("https://example.com:http"), None);
+        assert_eq!(origin_of("example.com"), None);
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        assert!(rule_matches("https://example.com", "https://example.com"));
+        assert!(!rule_matches("https://example.com", "http://example.com"));
+        assert!(rule_matches(
+            "https://*.example.com",
+            "https://api.example.com"
+        ));
+        assert!(rule_matches(
+            "https://*.example.com",
+            "https://a.b.example.com"
+        ));
+        assert!(!rule_matches(
+            "https://*.example.com",
+            "https://example.com"
+        ));
+        assert!(!rule_matches(
+            "https://*.example.com",
+            "https://badexample.com"
+        ));
+        assert!(!rule_matches(
+            "https://*.example.com",
+            "https://api.example.com:8443"
+        ));
+    }
+
+    #[test]
+    fn test_check_order() {
+        let mut policy = NetworkPolicy {
+            offline: false,
+            allow: vec!["https://*.example.com".into()],
+            deny: vec!["https://ads.example.com".into()],
+        };
+        assert_eq!(
+            policy.check("https://api.example.com/x").unwrap(),
+            "https://api.example.com"
+        );
+        assert_eq!(
+            policy.check("https://ads.example.com/x"),
+            Err(NetworkError::PolicyDenied)
+        );
+        assert_eq!(
+            policy.check("https://other.org"),
+            Err(NetworkError::PolicyDenied)
+        );
+        assert_eq!(policy.check("not a url"), Err(NetworkError::InvalidUrl));
+
+        policy.offline = true;
+        assert_eq!(
+            policy.check("https://api.example.com/x"),
+            Err(NetworkError::Offline)
+        );
+
+        // Empty allow list allows everything not denied
+        assert!(NetworkPolicy::default().check("https://other.org").is_ok());
+    }
+
+    #[test]
+    fn test_validate() {
+        let policy = NetworkPolicy {
+            offline: false,
+            allow: vec![
+                "https://*.example.com".into(),
+                "http://localhost:8080".into(),
+            ],
+            deny: vec!["http://[::1]:9999".into()],
+        };
+        assert!(policy.validate().is_ok());
+
+        for bad in [
+            "https://Example.com",
+            "https://example.com/",
+            "https://example.com:443",
+            "*.example.com",
+        ] {
+            let policy = NetworkPolicy {
+                deny: vec![bad.into()],
+                ..Default::default()
+            };
+            assert!(policy.validate().is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_policy_json_defaults() {
+        let policy = NetworkPolicy::from_json(br#"{"offline":true}"#).unwrap();
+        assert!(policy.offline);
+        assert!(policy.allow.is_empty());
+        assert_eq!(NetworkPolicy::from_json(&policy.to_json()), Some(policy));
+    }
+}
@@ -0,0 +1,764 @@
+//! Service client with retry and failover
+//!
+//! Services can be restarted at any time (updates, supervisor restarts,
+//! crash recovery). A process calling one has to cope with the endpoint
+//! disappearing, the service refusing requests while it drains, and replies
+//! that never arrive. `ServiceClient` does this once for every caller:
+//!
+//! - Looks the service up with init and acquires a capability to it before
+//!   the first call, and again after a failure that suggests a restart.
+//!   Init replies on the process's lookup-reply endpoint (see
+//!   [`lookup_reply_slot`]).
+//! - Times out replies that do not arrive.
+//! - Retries transient failures with exponential backoff plus jitter, so
+//!   clients of a restarted service do not all come back at once.
+//! - Sends each request with a deadline: the end of its timeout, or the
+//!   deadline of the request the caller is itself handling if that is
+//!   sooner. Services pass it on to their own calls, so once the client
+//!   gives up, work queued for it anywhere along the chain is dropped.
+//! - Stamps each try with a fresh correlation ID, so a late reply to an
+//!   earlier try is not taken for the reply to this one.
+//!
+//! Request and reply payloads are opaque bytes; replies are matched on the
+//! convention that the reply tag is the request tag plus one, and on the
+//! correlation ID when the service echoes one. Whether a reply
+//! means "retry later" is protocol-specific, so it is decided by a check
+//! passed to [`ServiceClient::with_retry_check`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut vfs = ServiceClient::new("vfs", VFS_ENDPOINT_SLOT, VFS_RESPONSE_SLOT)
+//!     .with_retry_check(zos_vfs::ipc::is_retry_response);
+//! let reply = vfs.call(MSG_VFS_READ, &request)?;
+//! ```
+
+use alloc::string::String;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::error::{E_BADF, E_NOENT};
+use crate::init::{MSG_LOOKUP_RESPONSE, MSG_LOOKUP_SERVICE};
+use crate::slots::INIT_ENDPOINT_SLOT;
+use crate::{ObjectType, ReceivedMessage};
+
+/// Errors from a service call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallError {
+    /// The service is not registered with init (it may be restarting)
+    NotFound,
+    /// A capability to the service could not be acquired
+    CapabilityDenied,
+    /// The service's endpoint no longer exists (the service was restarted)
+    EndpointGone,
+    /// No reply arrived in time
+    Timeout,
+    /// The service asked for the request to be retried (it is draining)
+    Retry,
+    /// Sending failed with this syscall error code
+    Send(u32),
+}
+
+impl CallError {
+    /// Whether the call may succeed if tried again.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            CallError::NotFound | CallError::EndpointGone | CallError::Timeout | CallError::Retry
+        )
+    }
+
+    /// Whether the service should be looked up again before the next try.
+    fn needs_reconnect(&self) -> bool {
+        matches!(self, CallError::EndpointGone | CallError::Timeout)
+    }
+}
+
+/// How hard a [`ServiceClient`] tries before giving up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total tries per call, including the first
+    pub max_attempts: u32,
+    /// How long to wait for each reply
+    pub timeout_ns: u64,
+    /// Backoff before the first retry; doubles on each further retry
+    pub base_backoff_ns: u64,
+    /// Upper bound on the backoff
+    pub max_backoff_ns: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            timeout_ns: 5_000_000_000,
+            base_backoff_ns: 50_000_000,
+            max_backoff_ns: 2_000_000_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before retry number `retry` (1 for the first retry).
+    ///
+    /// Half of the exponential delay is fixed and half is taken from
+    /// `jitter`, so retries spread out without ever retrying immediately.
+    pub fn backoff_ns(&self, retry: u32, jitter: u64) -> u64 {
+        let exponent = retry.saturating_sub(1).min(32);
+        let delay = self
+            .base_backoff_ns
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_backoff_ns);
+        let half = delay / 2;
+        half + jitter % (delay - half + 1)
+    }
+}
+
+/// The primitives a [`ServiceClient`] needs from the system.
+///
+/// [`SyscallTransport`] is the real implementation; tests substitute a
+/// scripted one.
+pub trait Transport {
+    /// Capability slot for calling `service`, whose endpoint init reported
+    /// as `endpoint_id`.
+    fn acquire(&mut self, service: &str, endpoint_id: u64) -> Result<u32, CallError>;
+    /// Send a message (error codes as returned by `send`).
+    fn send(&mut self, slot: u32, tag: u32, data: &[u8]) -> Result<(), u32>;
+    /// Take the next message from `slot`, if any.
+    fn receive(&mut self, slot: u32) -> Option<ReceivedMessage>;
+    /// Slot on which init's lookup replies arrive.
+    fn lookup_reply_slot(&mut self) -> Option<u32>;
+    /// Current uptime.
+    fn now_ns(&self) -> u64;
+    /// Let other processes run while waiting.
+    fn wait(&mut self);
+    /// Deadline carried by messages this process sends.
+    fn deadline(&self) -> Option<u64> {
+        None
+    }
+    /// Set the deadline carried by messages this process sends.
+    fn set_deadline(&mut self, _deadline_ns: Option<u64>) {}
+    /// Correlation ID carried by messages this process sends.
+    fn correlation_id(&self) -> Option<u32> {
+        None
+    }
+    /// Set the correlation ID carried by messages this process sends.
+    fn set_correlation_id(&mut self, _correlation_id: Option<u32>) {}
+}
+
+/// [`Transport`] over the process's syscalls.
+///
+/// Service endpoint capabilities are granted at spawn, so `acquire` hands
+/// back the well-known slot the client was created with.
+pub struct SyscallTransport {
+    endpoint_slot: u32,
+}
+
+impl SyscallTransport {
+    /// Transport calling a service through `endpoint_slot`.
+    pub fn new(endpoint_slot: u32) -> Self {
+        Self { endpoint_slot }
+    }
+}
+
+impl Transport for SyscallTransport {
+    fn acquire(&mut self, _service: &str, _endpoint_id: u64) -> Result<u32, CallError> {
+        Ok(self.endpoint_slot)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn send(&mut self, slot: u32, tag: u32, data: &[u8]) -> Result<(), u32> {
+        crate::send(slot, tag, data)
+    }
+
+    // The host stubs never deliver a reply; fail rather than time out
+    #[cfg(not(target_arch = "wasm32"))]
+    fn send(&mut self, _slot: u32, _tag: u32, _data: &[u8]) -> Result<(), u32> {
+        Err(crate::error::E_NOSYS)
+    }
+
+    fn receive(&mut self, slot: u32) -> Option<ReceivedMessage> {
+        crate::receive(slot).ok()
+    }
+
+    fn lookup_reply_slot(&mut self) -> Option<u32> {
+        lookup_reply_slot()
+    }
+
+    fn now_ns(&self) -> u64 {
+        crate::get_time()
+    }
+
+    fn wait(&mut self) {
+        crate::yield_now();
+    }
+
+    fn deadline(&self) -> Option<u64> {
+        crate::current_deadline()
+    }
+
+    fn set_deadline(&mut self, deadline_ns: Option<u64>) {
+        crate::set_deadline(deadline_ns);
+    }
+
+    fn correlation_id(&self) -> Option<u32> {
+        crate::current_correlation_id()
+    }
+
+    fn set_correlation_id(&mut self, correlation_id: Option<u32>) {
+        crate::set_correlation_id(correlation_id);
+    }
+}
+
+/// A connection to the current instance of the service.
+#[derive(Clone, Copy, Debug)]
+struct Connection {
+    slot: u32,
+}
+
+/// Client for a restartable service. See the module docs.
+pub struct ServiceClient<T: Transport = SyscallTransport> {
+    service: String,
+    transport: T,
+    reply_slot: u32,
+    policy: RetryPolicy,
+    is_retry: fn(&ReceivedMessage) -> bool,
+    connection: Option<Connection>,
+    /// Correlation ID of the last try
+    last_correlation: u32,
+    /// xorshift state for backoff jitter
+    rng: u64,
+}
+
+impl ServiceClient<SyscallTransport> {
+    /// Client for `service`, called through `endpoint_slot`, with replies
+    /// arriving on `reply_slot`.
+    pub fn new(service: &str, endpoint_slot: u32, reply_slot: u32) -> Self {
+        let seed = ((crate::get_pid() as u64) << 32) ^ crate::get_time();
+        Self::with_transport(service, SyscallTransport::new(endpoint_slot), reply_slot)
+            .with_seed(seed)
+    }
+}
+
+impl<T: Transport> ServiceClient<T> {
+    /// Client using a custom transport.
+    pub fn with_transport(service: &str, transport: T, reply_slot: u32) -> Self {
+        Self {
+            service: String::from(service),
+            transport,
+            reply_slot,
+            policy: RetryPolicy::default(),
+            is_retry: |_| false,
+            connection: None,
+            last_correlation: 0,
+            rng: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    /// Set the retry policy.
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set how to recognize a reply asking for the request to be retried.
+    pub fn with_retry_check(mut self, is_retry: fn(&ReceivedMessage) -> bool) -> Self {
+        self.is_retry = is_retry;
+        self
+    }
+
+    /// Seed the backoff jitter.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        // xorshift state must be non-zero
+        self.rng = seed | 1;
+        self
+    }
+
+    /// Name of the service.
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    /// The retry policy in use.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// The transport (for inspection in tests).
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Whether a connection to the service is cached.
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    /// Forget the cached connection; the next call looks the service up.
+    pub fn disconnect(&mut self) {
+        self.connection = None;
+    }
+
+    /// Send a request and wait for its reply, retrying transient failures.
+    ///
+    /// Returns the last error once `max_attempts` tries have failed, or
+    /// `Timeout` once the deadline inherited from the caller has passed.
+    pub fn call(&mut self, tag: u32, data: &[u8]) -> Result<ReceivedMessage, CallError> {
+        let inherited = self.transport.deadline();
+        let correlation = self.transport.correlation_id();
+        let result = self.call_within(inherited, tag, data);
+        // Receiving replies replaced the caller's deadline and correlation ID
+        self.transport.set_deadline(inherited);
+        self.transport.set_correlation_id(correlation);
+        result
+    }
+
+    fn call_within(
+        &mut self,
+        inherited: Option<u64>,
+        tag: u32,
+        data: &[u8],
+    ) -> Result<ReceivedMessage, CallError> {
+        let mut last_error = CallError::Timeout;
+        for attempt in 0..self.policy.max_attempts.max(1) {
+            if attempt > 0 {
+                let jitter = self.next_jitter();
+                self.sleep_ns(self.policy.backoff_ns(attempt, jitter));
+            }
+            let timeout = self
+                .transport
+                .now_ns()
+                .saturating_add(self.policy.timeout_ns);
+            let deadline = match inherited {
+                Some(inherited) if inherited <= self.transport.now_ns() => {
+                    return Err(CallError::Timeout)
+                }
+                Some(inherited) => inherited.min(timeout),
+                None => timeout,
+            };
+            match self.try_call(tag, data, deadline) {
+                Ok(reply) => return Ok(reply),
+                Err(e) if e.is_transient() => {
+                    crate::debug(&alloc::format!(
+                        "[client] {} call 0x{:x} failed ({:?}), attempt {}/{}",
+                        self.service,
+                        tag,
+                        e,
+                        attempt + 1,
+                        self.policy.max_attempts
+                    ));
+                    if e.needs_reconnect() {
+                        self.connection = None;
+                    }
+                    last_error = e;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error)
+    }
+
+    /// One try: connect if needed, send, wait for the reply until `deadline`.
+    fn try_call(
+        &mut self,
+        tag: u32,
+        data: &[u8],
+        deadline: u64,
+    ) -> Result<ReceivedMessage, CallError> {
+        let connection = self.connect()?;
+        // 0 means no correlation ID
+        self.last_correlation = self.last_correlation.wrapping_add(1).max(1);
+        let correlation = Some(self.last_correlation);
+        self.transport.set_deadline(Some(deadline));
+        self.transport.set_correlation_id(correlation);
+        let sent = self.transport.send(connection.slot, tag, data);
+        self.transport.set_deadline(None);
+        self.transport.set_correlation_id(None);
+        sent.map_err(send_error)?;
+        let reply = self.wait_for(self.reply_slot, tag + 1, correlation, deadline)?;
+        if (self.is_retry)(&reply) {
+            return Err(CallError::Retry);
+        }
+        Ok(reply)
+    }
+
+    /// Look the service up and acquire a capability, unless connected.
+    fn connect(&mut self) -> Result<Connection, CallError> {
+        if let Some(connection) = self.connection {
+            return Ok(connection);
+        }
+
+        // Lookup: [name_len: u8, name: [u8]]
+        // Look for the reply slot first, so a process without one fails fast
+        let reply_slot = self
+            .transport
+            .lookup_reply_slot()
+            .ok_or(CallError::CapabilityDenied)?;
+        let name = self.service.as_bytes();
+        let mut request = alloc::vec::Vec::with_capacity(1 + name.len());
+        request.push(name.len() as u8);
+        request.extend_from_slice(name);
+        self.transport
+            .send(INIT_ENDPOINT_SLOT, MSG_LOOKUP_SERVICE, &request)
+            .map_err(send_error)?;
+
+        // Response: [found: u8, endpoint_id_low: u32, endpoint_id_high: u32]
+        let deadline = self
+            .transport
+            .now_ns()
+            .saturating_add(self.policy.timeout_ns);
+        let response = self.wait_for(reply_slot, MSG_LOOKUP_RESPONSE, None, deadline)?;
+        let data = &response.data;
+        if data.len() < 9 || data[0] == 0 {
+            return Err(CallError::NotFound);
+        }
+        let low = u32::from_le_bytes([data[1], data[2], data[3], data[4]]) as u64;
+        let high = u32::from_le_bytes([data[5], data[6], data[7], data[8]]) as u64;
+
+        let slot = self.transport.acquire(&self.service, (high << 32) | low)?;
+        let connection = Connection { slot };
+        self.connection = Some(connection);
+        Ok(connection)
+    }
+
+    /// Wait for a message tagged `tag` on `slot`, dropping anything else.
+    ///
+    /// With a `correlation` ID, a message echoing a different one is dropped
+    /// too. One carrying none is taken: not every service echoes them.
+    fn wait_for(
+        &mut self,
+        slot: u32,
+        tag: u32,
+        correlation: Option<u32>,
+        deadline: u64,
+    ) -> Result<ReceivedMessage, CallError> {
+        loop {
+            while let Some(msg) = self.transport.receive(slot) {
+                let answers = correlation.is_none()
+                    || msg.correlation_id.is_none()
+                    || msg.correlation_id == correlation;
+                if msg.tag == tag && answers {
+                    return Ok(msg);
+                }
+                // A late reply to an earlier, timed-out try
+                crate::debug(&alloc::format!(
+                    "[client] {}: dropping stray message 0x{:x} from PID {}",
+                    self.service,
+                    msg.tag,
+                    msg.from_pid
+                ));
+            }
+            if self.transport.now_ns() >= deadline {
+                return Err(CallError::Timeout);
+            }
+            self.transport.wait();
+        }
+    }
+
+    fn sleep_ns(&mut self, duration_ns: u64) {
+        let until = self.transport.now_ns().saturating_add(duration_ns);
+        while self.transport.now_ns() < until {
+            self.transport.wait();
+        }
+    }
+
+    fn next_jitter(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+}
+
+/// Slot of this process's lookup-reply endpoint.
+///
+/// The supervisor creates the endpoint at spawn, owned by init, and gives
+/// the process a receive-only capability to it. Which slot that lands in
+/// depends on the services running at the time, so the first call looks
+/// for the one receive-only endpoint capability and later calls reuse it.
+pub fn lookup_reply_slot() -> Option<u32> {
+    static SLOT: AtomicU32 = AtomicU32::new(u32::MAX);
+
+    let cached = SLOT.load(Ordering::Relaxed);
+    if cached != u32::MAX {
+        return Some(cached);
+    }
+    let slot = crate::list_caps()
+        .into_iter()
+        .filter(|cap| cap.object_type == ObjectType::Endpoint as u8)
+        .filter_map(|cap| crate::cap_inspect(cap.slot))
+        .find(|cap| cap.can_read && !cap.can_write)?
+        .slot;
+    SLOT.store(slot, Ordering::Relaxed);
+    Some(slot)
+}
+
+/// Classify a `send` error code.
+fn send_error(code: u32) -> CallError {
+    match code {
+        E_BADF | E_NOENT => CallError::EndpointGone,
+        code => CallError::Send(code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::E_NOSYS;
+    use alloc::collections::VecDeque;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    const SERVICE_SLOT: u32 = 3;
+    const REPLY_SLOT: u32 = 4;
+    const LOOKUP_REPLY_SLOT: u32 = 6;
+    const TAG: u32 = 0x8012;
+    const TICK_NS: u64 = 1_000_000;
+
+    /// A scripted service: each send to the service slot pops the next
+    /// outcome.
+    #[derive(Default)]
+    struct Script {
+        now_ns: u64,
+        registered: bool,
+        no_reply_endpoint: bool,
+        outcomes: VecDeque<Outcome>,
+        inbox: Vec<(u32, ReceivedMessage)>,
+        lookups: u32,
+        requests: u32,
+        deadline: Option<u64>,
+        request_deadlines: Vec<Option<u64>>,
+        correlation: Option<u32>,
+        request_correlations: Vec<Option<u32>>,
+    }
+
+    enum Outcome {
+        Reply(&'static [u8]),
+        /// Reply, after a late reply to the previous try
+        AfterLate(&'static [u8]),
+        SendError(u32),
+        Silent,
+    }
+
+    fn message(tag: u32, data: &[u8]) -> ReceivedMessage {
+        ReceivedMessage {
+            from_pid: 3,
+            tag,
+            cap_slots: Vec::new(),
+            correlation_id: None,
+            data: data.to_vec(),
+        }
+    }
+
+    fn reply(tag: u32, data: &[u8], correlation_id: Option<u32>) -> ReceivedMessage {
+        ReceivedMessage {
+            correlation_id,
+            ..message(tag + 1, data)
+        }
+    }
+
+    impl Transport for Script {
+        fn acquire(&mut self, _service: &str, _endpoint_id: u64) -> Result<u32, CallError> {
+            Ok(SERVICE_SLOT)
+        }
+
+        fn send(&mut self, slot: u32, tag: u32, _data: &[u8]) -> Result<(), u32> {
+            if slot == INIT_ENDPOINT_SLOT {
+                self.lookups += 1;
+                let mut reply = vec![self.registered as u8];
+                reply.extend_from_slice(&7u32.to_le_bytes());
+                reply.extend_from_slice(&0u32.to_le_bytes());
+                self.inbox
+                    .push((LOOKUP_REPLY_SLOT, message(MSG_LOOKUP_RESPONSE, &reply)));
+                return Ok(());
+            }
+            self.requests += 1;
+            self.request_deadlines.push(self.deadline);
+            let previous = self.request_correlations.last().copied().flatten();
+            self.request_correlations.push(self.correlation);
+            match self.outcomes.pop_front().unwrap_or(Outcome::Silent) {
+                Outcome::Reply(data) => self
+                    .inbox
+                    .push((REPLY_SLOT, reply(tag, data, self.correlation))),
+                Outcome::AfterLate(data) => {
+                    self.inbox.push((REPLY_SLOT, reply(tag, b"late", previous)));
+                    self.inbox
+                        .push((REPLY_SLOT, reply(tag, data, self.correlation)));
+                }
+                Outcome::SendError(code) => return Err(code),
+                Outcome::Silent => {}
+            }
+            Ok(())
+        }
+
+        fn receive(&mut self, slot: u32) -> Option<ReceivedMessage> {
+            let index = self.inbox.iter().position(|(s, _)| *s == slot)?;
+            Some(self.inbox.remove(index).1)
+        }
+
+        fn lookup_reply_slot(&mut self) -> Option<u32> {
+            (!self.no_reply_endpoint).then_some(LOOKUP_REPLY_SLOT)
+        }
+
+        fn now_ns(&self) -> u64 {
+            self.now_ns
+        }
+
+        fn wait(&mut self) {
+            self.now_ns += TICK_NS;
+        }
+
+        fn deadline(&self) -> Option<u64> {
+            self.deadline
+        }
+
+        fn set_deadline(&mut self, deadline_ns: Option<u64>) {
+            self.deadline = deadline_ns;
+        }
+
+        fn correlation_id(&self) -> Option<u32> {
+            self.correlation
+        }
+
+        fn set_correlation_id(&mut self, correlation_id: Option<u32>) {
+            self.correlation = correlation_id;
+        }
+    }
+
+    fn scripted(outcomes: Vec<Outcome>) -> ServiceClient<Script> {
+        let script = Script {
+            registered: true,
+            outcomes: outcomes.into(),
+            ..Default::default()
+        };
+        ServiceClient::with_transport("vfs", script, REPLY_SLOT)
+            .with_policy(RetryPolicy {
+                max_attempts: 3,
+                timeout_ns: 10 * TICK_NS,
+                base_backoff_ns: 4 * TICK_NS,
+                max_backoff_ns: 16 * TICK_NS,
+            })
+            .with_retry_check(|msg| msg.data == b"retry")
+            .with_seed(42)
+    }
+
+    #[test]
+    fn test_call_connects_once() {
+        let mut client = scripted(vec![Outcome::Reply(b"a"), Outcome::Reply(b"b")]);
+        assert_eq!(client.call(TAG, b"").unwrap().data, b"a");
+        assert_eq!(client.call(TAG, b"").unwrap().data, b"b");
+        assert_eq!(client.transport().lookups, 1);
+    }
+
+    #[test]
+    fn test_call_retries_drain_and_reconnects_after_restart() {
+        let mut client = scripted(vec![
+            Outcome::Reply(b"retry"),
+            Outcome::SendError(E_BADF),
+            Outcome::Reply(b"ok"),
+        ]);
+        let reply = client.call(TAG, b"").unwrap();
+        assert_eq!(reply.data, b"ok");
+        assert_eq!(reply.tag, TAG + 1);
+        assert_eq!(client.transport().requests, 3);
+        // Only the dead endpoint forces a new lookup
+        assert_eq!(client.transport().lookups, 2);
+    }
+
+    #[test]
+    fn test_call_gives_up_after_max_attempts() {
+        let mut client = scripted(vec![Outcome::Silent, Outcome::Silent, Outcome::Silent]);
+        assert_eq!(client.call(TAG, b"").unwrap_err(), CallError::Timeout);
+        assert_eq!(client.transport().requests, 3);
+        assert!(!client.is_connected());
+
+        // Permanent errors are not retried
+        let mut client = scripted(vec![Outcome::SendError(E_NOSYS)]);
+        assert_eq!(client.call(TAG, b"").unwrap_err(), CallError::Send(E_NOSYS));
+        assert_eq!(client.transport().requests, 1);
+    }
+
+    #[test]
+    fn test_call_propagates_deadline() {
+        // Each try carries the end of its own timeout
+        let mut client = scripted(vec![Outcome::Reply(b"a")]);
+        client.call(TAG, b"").unwrap();
+        assert_eq!(
+            client.transport().request_deadlines,
+            vec![Some(10 * TICK_NS)]
+        );
+        assert_eq!(client.transport().deadline, None);
+
+        // A sooner deadline inherited from the caller wins, and survives the call
+        let mut client = scripted(vec![Outcome::Reply(b"a")]);
+        client.transport.deadline = Some(3 * TICK_NS);
+        client.call(TAG, b"").unwrap();
+        assert_eq!(
+            client.transport().request_deadlines,
+            vec![Some(3 * TICK_NS)]
+        );
+        assert_eq!(client.transport().deadline, Some(3 * TICK_NS));
+
+        // Once it has passed, nothing is sent
+        let mut client = scripted(vec![Outcome::Silent, Outcome::Silent]);
+        client.transport.deadline = Some(2 * TICK_NS);
+        assert_eq!(client.call(TAG, b"").unwrap_err(), CallError::Timeout);
+        assert_eq!(client.transport().requests, 1);
+    }
+
+    #[test]
+    fn test_call_matches_reply_by_correlation_id() {
+        // The late reply to the first try has the right tag, but not its ID
+        let mut client = scripted(vec![Outcome::Silent, Outcome::AfterLate(b"ok")]);
+        client.transport.correlation = Some(77);
+        assert_eq!(client.call(TAG, b"").unwrap().data, b"ok");
+        assert_eq!(
+            client.transport().request_correlations,
+            vec![Some(1), Some(2)]
+        );
+        // The caller's own ID survives the call
+        assert_eq!(client.transport().correlation, Some(77));
+
+        // Replies from services that echo nothing are still taken
+        let mut client = scripted(vec![]);
+        client
+            .transport
+            .inbox
+            .push((REPLY_SLOT, reply(TAG, b"a", None)));
+        client.transport.outcomes.push_back(Outcome::Silent);
+        assert_eq!(client.call(TAG, b"").unwrap().data, b"a");
+    }
+
+    #[test]
+    fn test_unregistered_service() {
+        let mut client = scripted(vec![]);
+        client.transport.registered = false;
+        assert_eq!(client.call(TAG, b"").unwrap_err(), CallError::NotFound);
+        assert_eq!(client.transport().lookups, 3);
+        assert_eq!(client.transport().requests, 0);
+    }
+
+    #[test]
+    fn test_lookup_without_reply_endpoint() {
+        let mut client = scripted(vec![Outcome::Reply(b"a")]);
+        client.transport.no_reply_endpoint = true;
+        assert_eq!(
+            client.call(TAG, b"").unwrap_err(),
+            CallError::CapabilityDenied
+        );
+        assert_eq!(client.transport().lookups, 0);
+    }
+
+    #[test]
+    fn test_backoff_bounds() {
+        let policy = RetryPolicy {
+            base_backoff_ns: 100,
+            max_backoff_ns: 1_000,
+            ..Default::default()
+        };
+        for jitter in [0, 1, 57, u64::MAX] {
+            assert!((50..=100).contains(&policy.backoff_ns(1, jitter)));
+            assert!((100..=200).contains(&policy.backoff_ns(2, jitter)));
+            assert!((500..=1_000).contains(&policy.backoff_ns(40, jitter)));
+        }
+    }
+}
@@ -18,6 +18,8 @@ extern crate alloc;
 // Module Organization
 // ============================================================================
 
+pub mod client;
+pub mod pool;
 pub mod syscalls;
 pub mod types;
 
@@ -56,6 +58,10 @@ pub mod error {
     pub const E_EXIST: u32 = 9;
     /// Buffer overflow
     pub const E_OVERFLOW: u32 = 10;
+    /// Timed out
+    pub const E_TIMEDOUT: u32 = 11;
+    /// The peer died before answering
+    pub const E_PEERDIED: u32 = 12;
 
     // =========================================================================
     // Typed Syscall Errors
@@ -147,26 +153,38 @@ pub mod error {
 }
 
 // Re-export types
-pub use types::{CapInfo, Permissions, ProcessInfo, ReceivedMessage};
+pub use types::{
+    CapInfo, Permissions, ProcessInfo, ProcessSnapshot, ProcessUsage, ReceivedMessage,
+};
 
 // Re-export ObjectType from zos-ipc (single source of truth for capability object types)
 pub use zos_ipc::ObjectType;
 
 // Re-export core syscalls
 pub use syscalls::{
-    call, cap_delete, cap_derive, cap_grant, cap_inspect, cap_revoke, cap_revoke_from,
-    console_write, create_endpoint, create_endpoint_for, debug, exit, get_pid, get_time,
-    get_wallclock, kill, list_caps, list_processes, load_binary, receive, receive_blocking,
-    receive_opt, register_process, reply, send, send_with_caps, spawn_process, yield_now,
+    bind_name, broadcast_create, call, call_timeout, cap_delete, cap_derive, cap_grant,
+    cap_inspect, cap_revoke, cap_revoke_from, console_write, create_endpoint, create_endpoint_for,
+    current_correlation_id, current_deadline, debug, exit, get_pid, get_time, get_wallclock, kill,
+    kill_group, list_caps, list_processes, load_binary, notify_create, poll_notification,
+    proc_read_mem, proc_state, process_usage, publish, receive, receive_blocking, receive_opt,
+    register_process, register_process_in_group, reply, send, send_named, send_with_caps,
+    set_correlation_id, set_deadline, set_memory_limit, set_priority, setpgid, signal,
+    spawn_process, subscribe, timer_cancel, timer_create, unsubscribe, wait_notification,
+    yield_now,
 };
 
 // Re-export typed error types
 pub use error::{CapError, ListError, RecvError};
 
+// Re-export the retrying service client
+pub use client::{
+    lookup_reply_slot, CallError, RetryPolicy, ServiceClient, SyscallTransport, Transport,
+};
+
 // Re-export storage syscalls
 pub use syscalls::storage::{
-    storage_delete_async, storage_exists_async, storage_list_async, storage_read_async,
-    storage_write_async,
+    storage_batch_write_async, storage_cancel, storage_delete_async, storage_exists_async,
+    storage_list_async, storage_read_async, storage_write_async,
 };
 
 // Re-export keystore syscalls
@@ -178,6 +196,9 @@ pub use syscalls::keystore::{
 // Re-export network syscalls
 pub use syscalls::network::network_fetch_async;
 
+// Re-export shared memory syscalls
+pub use syscalls::shm::{shm_create, shm_fetch, shm_flush, shm_map, shm_unmap, SharedMemory};
+
 
 // ============================================================================
 // IPC Message Constants (re-exported from zos-ipc)
@@ -185,10 +206,10 @@ pub use syscalls::network::network_fetch_async;
 
 // Re-export all IPC modules for convenient access
 pub use zos_ipc::{
-    console, diagnostics, identity_cred, identity_key, identity_machine, identity_perm,
-    identity_prefs, identity_query, identity_remote, identity_session, identity_user, identity_zid,
-    init, kernel, keystore, net, permission, pid, pm, revoke_reason, slots, storage, supervisor,
-    syscall_error, vfs_dir, vfs_file, vfs_meta, vfs_quota,
+    console, diagnostics, identity_cred, identity_enroll, identity_key, identity_machine,
+    identity_perm, identity_prefs, identity_query, identity_remote, identity_session, identity_user,
+    identity_zid, init, kernel, keystore, net, permission, pid, pm, revoke_reason, services, slots,
+    storage, supervisor, syscall_error, update, vfs_dir, vfs_file, vfs_meta, vfs_quota,
 };
 
 /// Console input message tag - used by terminal for receiving keyboard input.
@@ -204,7 +225,7 @@ pub use zos_ipc::init::MSG_REGISTER_SERVICE;
 /// Lookup a service: data = [name_len: u8, name: [u8]]
 pub use zos_ipc::init::MSG_LOOKUP_SERVICE;
 
-/// Lookup response: data = [found: u8, endpoint_id_low: u32, endpoint_id_high: u32]
+/// Lookup response, on the lookup-reply endpoint: data = [found: u8, endpoint_id_low: u32, endpoint_id_high: u32]
 pub use zos_ipc::init::MSG_LOOKUP_RESPONSE;
 
 /// Request spawn: data = [name_len: u8, name: [u8]]
@@ -224,6 +245,10 @@ pub use zos_ipc::init::MSG_SERVICE_READY;
 /// Payload: [slot: u32, object_type: u8, object_id: u64, reason: u8]
 pub use zos_ipc::kernel::MSG_CAP_REVOKED;
 
+/// A timer armed with `timer_create` came due (sent from PID 0)
+/// Payload: [timer_id: u32]
+pub use zos_ipc::kernel::MSG_TIMER_FIRED;
+
 /// Revocation reason: Supervisor/user explicitly revoked the capability
 pub const REVOKE_REASON_EXPLICIT: u8 = zos_ipc::revoke_reason::EXPLICIT;
 /// Revocation reason: Capability expired
@@ -234,6 +259,17 @@ pub const REVOKE_REASON_PROCESS_EXIT: u8 = zos_ipc::revoke_reason::PROCESS_EXIT;
 /// Well-known slot for init's endpoint (every process gets this at spawn)
 pub use zos_ipc::slots::INIT_ENDPOINT_SLOT;
 
+/// Scheduling priority classes for `set_priority`
+pub use zos_ipc::priority;
+
+// =============================================================================
+// Request Control
+// =============================================================================
+
+/// Cancel the sender's in-flight requests to a service.
+/// Payload: [request_tag: u32] (0 = all requests)
+pub use zos_ipc::request::MSG_CANCEL_REQUEST;
+
 // =============================================================================
 // Storage Result IPC (delivered from supervisor via HAL async storage)
 // =============================================================================
@@ -242,11 +278,18 @@ pub use zos_ipc::slots::INIT_ENDPOINT_SLOT;
 /// Payload format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
 pub use zos_ipc::storage::MSG_STORAGE_RESULT;
 
+/// Several storage results for one process, delivered together
+/// Payload format: [count: u32, (len: u32, result: [u8; len])*]
+pub use zos_ipc::storage::MSG_STORAGE_RESULT_BATCH;
+
 /// Storage result types
 pub mod storage_result {
     pub use zos_ipc::storage::result::*;
 }
 
+/// Typed storage/keystore result envelope (encode/decode of the payload above)
+pub use zos_ipc::storage::{RequestId, ResultKind, StorageResult};
+
 // =============================================================================
 // Keystore Result IPC (delivered from supervisor via HAL async keystore)
 // =============================================================================
@@ -284,6 +327,14 @@ pub use zos_ipc::supervisor::MSG_SUPERVISOR_IPC_DELIVERY;
 /// Payload: [target_pid: u32, slot: u32, reason: u8]
 pub use zos_ipc::supervisor::MSG_SUPERVISOR_REVOKE_CAP;
 
+/// Supervisor requests PermissionService to delete an app's data.
+/// Payload: [user_id: u128, app_id: [u8]]
+pub use zos_ipc::supervisor::MSG_SUPERVISOR_DELETE_APP_DATA;
+
+/// Supervisor relays the user's answer to a PermissionService prompt.
+/// Payload: [prompt_id: u32, granted: u8]
+pub use zos_ipc::supervisor::MSG_SUPERVISOR_PROMPT_RESPONSE;
+
 // =============================================================================
 // Permission Protocol (03-security.md)
 // =============================================================================
@@ -0,0 +1,55 @@
+//! Process-wide message buffer pool
+//!
+//! `receive()` copies each payload into a buffer taken from this pool, and
+//! handlers that are done with a payload hand it back with [`recycle`].
+//! Processes on the bump allocator never get freed memory back, so reusing
+//! payload capacity is what keeps a long-running event loop's heap flat.
+
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use zos_allocator::{MessagePool, PoolStats};
+
+/// The pool behind a try-lock. WASM processes are single-threaded, so the
+/// lock only guards against reentry (e.g. a buffer recycled from inside a
+/// drop that runs while the pool is in use); a contended call simply
+/// bypasses the pool.
+struct SharedPool {
+    busy: AtomicBool,
+    pool: UnsafeCell<MessagePool>,
+}
+
+// SAFETY: all access to `pool` goes through `with_pool`, which holds `busy`.
+unsafe impl Sync for SharedPool {}
+
+static POOL: SharedPool = SharedPool {
+    busy: AtomicBool::new(false),
+    pool: UnsafeCell::new(MessagePool::new()),
+};
+
+fn with_pool<R>(f: impl FnOnce(&mut MessagePool) -> R) -> Option<R> {
+    if POOL.busy.swap(true, Ordering::Acquire) {
+        return None;
+    }
+    // SAFETY: `busy` was false, so no other reference to the pool exists
+    let result = f(unsafe { &mut *POOL.pool.get() });
+    POOL.busy.store(false, Ordering::Release);
+    Some(result)
+}
+
+/// A pooled buffer holding a copy of `data`.
+pub fn copy_of(data: &[u8]) -> Vec<u8> {
+    with_pool(|pool| pool.copy_of(data)).unwrap_or_else(|| data.to_vec())
+}
+
+/// Return a payload buffer so a later `receive()` can reuse its capacity.
+pub fn recycle(buf: Vec<u8>) {
+    // If the pool is busy the buffer is simply dropped
+    with_pool(|pool| pool.give(buf));
+}
+
+/// Pool counters for this process.
+pub fn stats() -> PoolStats {
+    with_pool(|pool| pool.stats()).unwrap_or_default()
+}
@@ -37,13 +37,13 @@ extern "C" {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn keystore_read_async(key: &str) -> Result<i64, i64> {
+pub fn keystore_read_async(key: &str) -> Result<u32, i64> {
     let key_bytes = key.as_bytes();
     unsafe {
         zos_send_bytes(key_bytes.as_ptr(), key_bytes.len() as u32);
         let result = zos_syscall(SYS_KEYSTORE_READ, key_bytes.len() as u32, 0, 0);
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -51,7 +51,7 @@ pub fn keystore_read_async(key: &str) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn keystore_read_async(_key: &str) -> Result<i64, i64> {
+pub fn keystore_read_async(_key: &str) -> Result<u32, i64> {
     Err(error::E_NOSYS as i64)
 }
 
@@ -68,7 +68,7 @@ pub fn keystore_read_async(_key: &str) -> Result<i64, i64> {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn keystore_write_async(key: &str, value: &[u8]) -> Result<i64, i64> {
+pub fn keystore_write_async(key: &str, value: &[u8]) -> Result<u32, i64> {
     let key_bytes = key.as_bytes();
     // Data format: [key_len: u32, key: [u8], value: [u8]]
     let mut data = Vec::with_capacity(4 + key_bytes.len() + value.len());
@@ -85,7 +85,7 @@ pub fn keystore_write_async(key: &str, value: &[u8]) -> Result<i64, i64> {
             0,
         );
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -93,7 +93,7 @@ pub fn keystore_write_async(key: &str, value: &[u8]) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn keystore_write_async(_key: &str, _value: &[u8]) -> Result<i64, i64> {
+pub fn keystore_write_async(_key: &str, _value: &[u8]) -> Result<u32, i64> {
     Err(error::E_NOSYS as i64)
 }
 
@@ -109,13 +109,13 @@ pub fn keystore_write_async(_key: &str, _value: &[u8]) -> Result<i64, i64> {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn keystore_delete_async(key: &str) -> Result<i64, i64> {
+pub fn keystore_delete_async(key: &str) -> Result<u32, i64> {
     let key_bytes = key.as_bytes();
     unsafe {
         zos_send_bytes(key_bytes.as_ptr(), key_bytes.len() as u32);
         let result = zos_syscall(SYS_KEYSTORE_DELETE, key_bytes.len() as u32, 0, 0);
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -123,7 +123,7 @@ pub fn keystore_delete_async(key: &str) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn keystore_delete_async(_key: &str) -> Result<i64, i64> {
+pub fn keystore_delete_async(_key: &str) -> Result<u32, i64> {
     Err(error::E_NOSYS as i64)
 }
 
@@ -140,13 +140,13 @@ pub fn keystore_delete_async(_key: &str) -> Result<i64, i64> {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn keystore_list_async(prefix: &str) -> Result<i64, i64> {
+pub fn keystore_list_async(prefix: &str) -> Result<u32, i64> {
     let prefix_bytes = prefix.as_bytes();
     unsafe {
         zos_send_bytes(prefix_bytes.as_ptr(), prefix_bytes.len() as u32);
         let result = zos_syscall(SYS_KEYSTORE_LIST, prefix_bytes.len() as u32, 0, 0);
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -154,7 +154,7 @@ pub fn keystore_list_async(prefix: &str) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn keystore_list_async(_prefix: &str) -> Result<i64, i64> {
+pub fn keystore_list_async(_prefix: &str) -> Result<u32, i64> {
     Err(error::E_NOSYS as i64)
 }
 
@@ -171,13 +171,13 @@ pub fn keystore_list_async(_prefix: &str) -> Result<i64, i64> {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn keystore_exists_async(key: &str) -> Result<i64, i64> {
+pub fn keystore_exists_async(key: &str) -> Result<u32, i64> {
     let key_bytes = key.as_bytes();
     unsafe {
         zos_send_bytes(key_bytes.as_ptr(), key_bytes.len() as u32);
         let result = zos_syscall(SYS_KEYSTORE_EXISTS, key_bytes.len() as u32, 0, 0);
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -185,6 +185,6 @@ pub fn keystore_exists_async(key: &str) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn keystore_exists_async(_key: &str) -> Result<i64, i64> {
+pub fn keystore_exists_async(_key: &str) -> Result<u32, i64> {
     Err(error::E_NOSYS as i64)
 }
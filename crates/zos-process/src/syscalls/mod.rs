@@ -7,16 +7,20 @@ use crate::error;
 // Import syscall numbers (re-exported from zos-ipc at crate root)
 #[allow(unused_imports)]
 use crate::{
-    SYS_CALL, SYS_CAP_DELETE, SYS_CAP_DERIVE, SYS_CAP_GRANT, SYS_CAP_INSPECT, SYS_CAP_LIST,
-    SYS_CAP_REVOKE, SYS_CONSOLE_WRITE, SYS_CREATE_ENDPOINT, SYS_CREATE_ENDPOINT_FOR, SYS_DEBUG,
-    SYS_DELETE_ENDPOINT, SYS_EXIT, SYS_KILL, SYS_LOAD_BINARY, SYS_PS, SYS_RECV, SYS_REGISTER_PROCESS,
-    SYS_REPLY, SYS_SEND, SYS_SEND_CAP, SYS_SPAWN_PROCESS, SYS_TIME, SYS_WALLCLOCK, SYS_YIELD,
+    PS_USAGE, SYS_CALL, SYS_CAP_DELETE, SYS_CAP_DERIVE, SYS_CAP_GRANT, SYS_CAP_INSPECT,
+    SYS_CAP_LIST, SYS_CAP_REVOKE, SYS_CONSOLE_WRITE, SYS_CREATE_ENDPOINT, SYS_CREATE_ENDPOINT_FOR,
+    SYS_DEBUG, SYS_DELETE_ENDPOINT, SYS_EXIT, SYS_KILL, SYS_LOAD_BINARY, SYS_PS, SYS_RECV,
+    SYS_REGISTER_PROCESS, SYS_REPLY, SYS_SEND, SYS_SEND_CAP, SYS_SPAWN_PROCESS, SYS_TIME,
+    SYS_WALLCLOCK, SYS_YIELD,
+};
+use crate::types::{
+    CapInfo, Permissions, ProcessInfo, ProcessSnapshot, ProcessUsage, ReceivedMessage,
 };
-use crate::types::{CapInfo, Permissions, ProcessInfo, ReceivedMessage};
 use alloc::vec::Vec;
 
 pub mod keystore;
 pub mod network;
+pub mod shm;
 pub mod storage;
 
 // ============================================================================
@@ -107,6 +111,74 @@ pub fn get_time() -> u64 {
     0
 }
 
+/// Set the deadline (uptime nanos) carried by messages this process sends.
+///
+/// The kernel replaces it with the deadline of each message received, so a
+/// service handling a request passes the client's deadline on to anything
+/// it calls without setting it. `None` clears it.
+#[cfg(target_arch = "wasm32")]
+pub fn set_deadline(deadline_ns: Option<u64>) {
+    use crate::SYS_SET_DEADLINE;
+
+    let deadline = deadline_ns.unwrap_or(0);
+    unsafe {
+        zos_syscall(SYS_SET_DEADLINE, deadline as u32, (deadline >> 32) as u32, 0);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_deadline(_deadline_ns: Option<u64>) {}
+
+/// Deadline of the request this process is working on, if it has one.
+#[cfg(target_arch = "wasm32")]
+pub fn current_deadline() -> Option<u64> {
+    use crate::SYS_GET_DEADLINE;
+
+    let deadline = unsafe {
+        let low = zos_syscall(SYS_GET_DEADLINE, 0, 0, 0);
+        let high = zos_syscall(SYS_GET_DEADLINE, 1, 0, 0);
+        ((high as u64) << 32) | (low as u64)
+    };
+    (deadline != 0).then_some(deadline)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn current_deadline() -> Option<u64> {
+    None
+}
+
+/// Set the correlation ID carried by messages this process sends.
+///
+/// The kernel replaces it with the correlation ID of each message
+/// received, so a reply sent straight away echoes the request's. A service
+/// answering later sets it back from what it kept of the request. `None`
+/// clears it.
+#[cfg(target_arch = "wasm32")]
+pub fn set_correlation_id(correlation_id: Option<u32>) {
+    use crate::SYS_SET_CORRELATION;
+
+    unsafe {
+        zos_syscall(SYS_SET_CORRELATION, correlation_id.unwrap_or(0), 0, 0);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_correlation_id(_correlation_id: Option<u32>) {}
+
+/// Correlation ID of the request this process is working on, if it has one.
+#[cfg(target_arch = "wasm32")]
+pub fn current_correlation_id() -> Option<u32> {
+    use crate::SYS_GET_CORRELATION;
+
+    let id = unsafe { zos_syscall(SYS_GET_CORRELATION, 0, 0, 0) } as u32;
+    (id != 0).then_some(id)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn current_correlation_id() -> Option<u32> {
+    None
+}
+
 /// Get wall-clock time in milliseconds since Unix epoch
 ///
 /// This is real time-of-day (can jump due to NTP sync).
@@ -184,11 +256,267 @@ pub fn kill(_target_pid: u32) -> Result<(), u32> {
     Err(error::E_NOSYS)
 }
 
+/// Kill the processes in a process group, except the caller.
+///
+/// Init and members of the group kill every other member; any other caller
+/// only the members it holds a Process capability for. A terminal kills the
+/// pipeline it started with `kill_group(get_pid())`.
+///
+/// # Arguments
+/// - `pgid`: ID of the group (its leader's PID)
+///
+/// # Returns
+/// - `Ok(count)`: Number of processes killed
+/// - `Err(code)`: Error code (`syscall_error::PERMISSION_DENIED` or
+///   `NOT_FOUND`)
+#[cfg(target_arch = "wasm32")]
+pub fn kill_group(pgid: u32) -> Result<u32, u32> {
+    use crate::KILL_GROUP;
+
+    let result = unsafe { zos_syscall(SYS_KILL, pgid, KILL_GROUP, 0) };
+    if result >= 0 {
+        Ok(result as u32)
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn kill_group(_pgid: u32) -> Result<u32, u32> {
+    Err(error::E_NOSYS)
+}
+
+/// Move a process to another process group.
+///
+/// Needs authority over the target: the caller is the target, Init, in the
+/// target's group or holds a Process capability for it. Joining an existing
+/// group also needs authority over that group.
+///
+/// # Arguments
+/// - `target_pid`: PID of the process
+/// - `pgid`: Group to join, or 0 for a new group led by the target
+///
+/// # Returns
+/// - `Ok(pgid)`: The group the process is now in
+/// - `Err(code)`: Error code (`syscall_error::PERMISSION_DENIED` or
+///   `NOT_FOUND`)
+#[cfg(target_arch = "wasm32")]
+pub fn setpgid(target_pid: u32, pgid: u32) -> Result<u32, u32> {
+    use crate::SYS_SETPGID;
+
+    let result = unsafe { zos_syscall(SYS_SETPGID, target_pid, pgid, 0) };
+    if result >= 0 {
+        Ok(result as u32)
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn setpgid(_target_pid: u32, _pgid: u32) -> Result<u32, u32> {
+    Err(error::E_NOSYS)
+}
+
+/// Set a process's scheduling priority class.
+///
+/// Like `kill`, this needs a Process capability for the target with write
+/// permission, or the caller must be Init (PID 1). A process may always
+/// lower its own priority, e.g. before starting background work.
+///
+/// # Arguments
+/// - `target_pid`: PID of the process
+/// - `priority`: A `zos_ipc::priority` class
+///
+/// # Returns
+/// - `Ok(())`: Priority was set
+/// - `Err(code)`: Error code (`syscall_error::PERMISSION_DENIED`,
+///   `NOT_FOUND` or `INVALID_ARGUMENT`)
+#[cfg(target_arch = "wasm32")]
+pub fn set_priority(target_pid: u32, priority: u8) -> Result<(), u32> {
+    use crate::SYS_SET_PRIORITY;
+
+    let result = unsafe { zos_syscall(SYS_SET_PRIORITY, target_pid, priority as u32, 0) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_priority(_target_pid: u32, _priority: u8) -> Result<(), u32> {
+    Err(error::E_NOSYS)
+}
+
+/// Set a process's memory limit, in bytes (rounded up to whole KiB).
+///
+/// A process whose memory grows past its limit is terminated by the kernel.
+/// Authority is as for `set_priority`: a process may always lower its own
+/// limit, e.g. to declare what it needs at startup.
+///
+/// # Arguments
+/// - `target_pid`: PID of the process
+/// - `limit`: Limit in bytes, or 0 for the kernel default
+///
+/// # Returns
+/// - `Ok(())`: Limit was set
+/// - `Err(code)`: Error code (`syscall_error::PERMISSION_DENIED`,
+///   `NOT_FOUND`, or `INVALID_ARGUMENT` if the process already uses more)
+#[cfg(target_arch = "wasm32")]
+pub fn set_memory_limit(target_pid: u32, limit: usize) -> Result<(), u32> {
+    use crate::SYS_SET_MEMORY_LIMIT;
+
+    let kib = limit.div_ceil(1024).min(u32::MAX as usize) as u32;
+    let result = unsafe { zos_syscall(SYS_SET_MEMORY_LIMIT, target_pid, kib, 0) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_memory_limit(_target_pid: u32, _limit: usize) -> Result<(), u32> {
+    Err(error::E_NOSYS)
+}
+
+/// Snapshot another process's runtime state, for a debugger.
+///
+/// Needs a Debugger capability, granted by PermissionService once the user
+/// approves the request.
+///
+/// # Returns
+/// - `Ok(snapshot)`: The process's state
+/// - `Err(code)`: Error code (`syscall_error::PERMISSION_DENIED` or
+///   `NOT_FOUND`)
+#[cfg(target_arch = "wasm32")]
+pub fn proc_state(target_pid: u32) -> Result<ProcessSnapshot, u32> {
+    use crate::SYS_PROC_STATE;
+
+    let mut buffer = [0u8; 256];
+    unsafe {
+        let result = zos_syscall(SYS_PROC_STATE, target_pid, 0, 0);
+        if result != 0 {
+            return Err(result as u32);
+        }
+        let len = zos_recv_bytes(buffer.as_mut_ptr(), buffer.len() as u32) as usize;
+        ProcessSnapshot::parse(&buffer[..len.min(buffer.len())]).ok_or(error::E_INVAL)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn proc_state(_target_pid: u32) -> Result<ProcessSnapshot, u32> {
+    Err(error::E_NOSYS)
+}
+
+/// Read another process's memory, for a debugger.
+///
+/// Needs a Debugger capability, as for `proc_state`.
+///
+/// # Arguments
+/// - `target_pid`: PID of the process
+/// - `addr`: Address in its linear memory
+/// - `len`: Bytes to read, at most `PROC_READ_MEM_MAX`
+///
+/// # Returns
+/// - `Ok(bytes)`: The bytes read
+/// - `Err(code)`: Error code (`syscall_error::PERMISSION_DENIED`,
+///   `NOT_FOUND`, `INVALID_ARGUMENT` or `NOT_SUPPORTED`)
+#[cfg(target_arch = "wasm32")]
+pub fn proc_read_mem(target_pid: u32, addr: u32, len: u32) -> Result<Vec<u8>, u32> {
+    use crate::SYS_PROC_READ_MEM;
+
+    unsafe {
+        let result = zos_syscall(SYS_PROC_READ_MEM, target_pid, addr, len);
+        if result < 0 {
+            return Err(result as u32);
+        }
+        let mut buffer = alloc::vec![0u8; result as usize];
+        let received = zos_recv_bytes(buffer.as_mut_ptr(), buffer.len() as u32) as usize;
+        buffer.truncate(received);
+        Ok(buffer)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn proc_read_mem(_target_pid: u32, _addr: u32, _len: u32) -> Result<Vec<u8>, u32> {
+    Err(error::E_NOSYS)
+}
+
 // ============================================================================
-// IPC Syscalls
+// Timer Syscalls
 // ============================================================================
 
-/// Send a message to an endpoint
+/// Arm a timer that delivers `kernel::MSG_TIMER_FIRED` (payload: the timer
+/// ID) to the endpoint in `endpoint_slot`, `delay_ms` from now and then
+/// every `period_ms` (0 = once).
+///
+/// The caller must be able to receive on the endpoint; normally it is the
+/// process's own input endpoint. Firings are coalesced: while one is still
+/// queued the timer does not queue another. Timers are cancelled when the
+/// process exits.
+///
+/// # Returns
+/// - `Ok(timer_id)`: Timer armed
+/// - `Err(code)`: Error (no such endpoint, or too many timers)
+#[cfg(target_arch = "wasm32")]
+pub fn timer_create(delay_ms: u32, period_ms: u32, endpoint_slot: u32) -> Result<u32, u32> {
+    use crate::SYS_TIMER_CREATE;
+
+    let result = unsafe { zos_syscall(SYS_TIMER_CREATE, delay_ms, period_ms, endpoint_slot) };
+    if result > 0 {
+        Ok(result as u32)
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn timer_create(_delay_ms: u32, _period_ms: u32, _endpoint_slot: u32) -> Result<u32, u32> {
+    Err(error::E_NOSYS)
+}
+
+/// Cancel a timer armed with [`timer_create`].
+#[cfg(target_arch = "wasm32")]
+pub fn timer_cancel(timer_id: u32) -> Result<(), u32> {
+    use crate::SYS_TIMER_CANCEL;
+
+    let result = unsafe { zos_syscall(SYS_TIMER_CANCEL, timer_id, 0, 0) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn timer_cancel(_timer_id: u32) -> Result<(), u32> {
+    Err(error::E_NOSYS)
+}
+
+// ============================================================================
+// IPC Syscalls
+// ============================================================================
+//
+// Ordering and fairness guarantees:
+//
+// - Messages on an endpoint are delivered in the order they were sent.
+// - An endpoint queues at most MAX_QUEUED_MESSAGES (256). A send to a full
+//   endpoint fails with `syscall_error::WOULD_BLOCK` and puts the sender in
+//   line. Free slots go to waiting senders in the order they first blocked;
+//   a sender that was not waiting queues behind them.
+// - Receivers sharing an endpoint are served the same way: a receive that
+//   finds nothing puts the receiver in line, and queued messages go to
+//   waiting receivers in the order they started waiting.
+// - A waiter keeps its place only while it retries: one that exits or does
+//   not retry within a second is dropped from the line. Retrying in a
+//   `yield_now()` loop is enough to hold the place, and no waiter can be
+//   overtaken indefinitely.
+
+/// Send a message to an endpoint.
+///
+/// Fails with `syscall_error::WOULD_BLOCK` (as `u32`) when the endpoint is
+/// full; retry to keep the place in line.
 #[cfg(target_arch = "wasm32")]
 pub fn send(endpoint_slot: u32, tag: u32, data: &[u8]) -> Result<(), u32> {
     unsafe {
@@ -207,8 +535,43 @@ pub fn send(_endpoint_slot: u32, _tag: u32, _data: &[u8]) -> Result<(), u32> {
     Ok(())
 }
 
+/// Send a message to a service by name.
+///
+/// The kernel resolves the name to a capability on first use and caches it,
+/// resolving again after the service restarts, so no capability has to be
+/// granted up front.
+#[cfg(target_arch = "wasm32")]
+pub fn send_named(service: &str, tag: u32, data: &[u8]) -> Result<(), u32> {
+    use crate::SYS_SEND_NAMED;
+
+    // Build payload: [name_len: u8, name: [u8], data: [u8]]
+    let name = service.as_bytes();
+    let mut payload = Vec::with_capacity(1 + name.len() + data.len());
+    payload.push(name.len() as u8);
+    payload.extend_from_slice(name);
+    payload.extend_from_slice(data);
+
+    unsafe {
+        zos_send_bytes(payload.as_ptr(), payload.len() as u32);
+        let result = zos_syscall(SYS_SEND_NAMED, tag, payload.len() as u32, 0);
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(result as u32)
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn send_named(_service: &str, _tag: u32, _data: &[u8]) -> Result<(), u32> {
+    Ok(())
+}
+
 /// Receive a message from an endpoint (non-blocking).
 ///
+/// When other receivers on the endpoint have been waiting longer, they get
+/// the queued messages first and this returns `NoMessage`.
+///
 /// # Returns
 /// - `Ok(msg)`: Successfully received a message
 /// - `Err(RecvError::NoMessage)`: No message available (try again later)
@@ -238,41 +601,17 @@ pub fn receive(endpoint_slot: u32) -> Result<ReceivedMessage, error::RecvError>
             return Err(RecvError::ParseError);
         }
 
-        // Parse message format:
-        // [from_pid: u32][tag: u32][num_caps: u8][cap_slots: u32*num_caps][data: ...]
-        // Minimum: 4 + 4 + 1 = 9 bytes
-        if len < 9 {
-            return Err(RecvError::ParseError);
-        }
-        let from_pid = u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
-        let tag = u32::from_le_bytes([buffer[4], buffer[5], buffer[6], buffer[7]]);
-        let num_caps = buffer[8] as usize;
-
-        // Parse capability slots with overflow check
-        let cap_data_len = num_caps.checked_mul(4).ok_or(RecvError::ParseError)?;
-        let data_start = 9usize.checked_add(cap_data_len).ok_or(RecvError::ParseError)?;
-        if (len as usize) < data_start {
-            return Err(RecvError::ParseError);
-        }
-
-        let mut cap_slots = Vec::with_capacity(num_caps);
-        for i in 0..num_caps {
-            let offset = 9 + i * 4;
-            let slot = u32::from_le_bytes([
-                buffer[offset],
-                buffer[offset + 1],
-                buffer[offset + 2],
-                buffer[offset + 3],
-            ]);
-            cap_slots.push(slot);
-        }
-
-        let data = buffer[data_start..len as usize].to_vec();
+        // Parse message format (see zos_ipc::frame):
+        // [from_pid: u32][tag: u32][num_caps: u8][cap_slots: u32*num_caps]
+        // [correlation_id: u32][data: ...]
+        let frame = zos_ipc::frame::Frame::decode(&buffer[..len as usize])
+            .map_err(|_| RecvError::ParseError)?;
         Ok(ReceivedMessage {
-            from_pid,
-            tag,
-            cap_slots,
-            data,
+            from_pid: frame.from_pid,
+            tag: frame.tag,
+            cap_slots: frame.cap_slots,
+            correlation_id: frame.correlation_id,
+            data: crate::pool::copy_of(frame.data),
         })
     }
 }
@@ -336,14 +675,13 @@ pub fn send_with_caps(
     data: &[u8],
     cap_slots: &[u32],
 ) -> Result<(), u32> {
+    // Data and capability slots go in one payload: the syscall takes the
+    // last bytes sent
+    let mut payload = Vec::with_capacity(data.len() + cap_slots.len() * 4);
+    payload.extend_from_slice(data);
+    payload.extend(cap_slots.iter().flat_map(|s| s.to_le_bytes()));
     unsafe {
-        // Send data first
-        zos_send_bytes(data.as_ptr(), data.len() as u32);
-        // Pack cap_slots into bytes and send
-        if !cap_slots.is_empty() {
-            let cap_bytes: Vec<u8> = cap_slots.iter().flat_map(|s| s.to_le_bytes()).collect();
-            zos_send_bytes(cap_bytes.as_ptr(), cap_bytes.len() as u32);
-        }
+        zos_send_bytes(payload.as_ptr(), payload.len() as u32);
         let result = zos_syscall(
             SYS_SEND_CAP,
             endpoint_slot,
@@ -370,35 +708,93 @@ pub fn send_with_caps(
 
 /// Call - send a message and wait for reply (RPC pattern)
 ///
+/// Waits as long as it takes; see `call_timeout`.
+pub fn call(endpoint_slot: u32, tag: u32, data: &[u8]) -> Result<ReceivedMessage, u32> {
+    call_timeout(endpoint_slot, tag, data, 0)
+}
+
+/// Call with a timeout - send a request and wait for its reply
+///
+/// Whoever receives the request answers it with `reply(from_pid, ..)`;
+/// the reply comes back to this call, not through an endpoint.
+///
 /// # Arguments
 /// - `endpoint_slot`: Capability slot for the destination endpoint
 /// - `tag`: Application-defined message tag
 /// - `data`: Request payload
+/// - `timeout_ms`: How long to wait for the reply (0 = no limit)
 ///
 /// # Returns
 /// - `Ok(ReceivedMessage)`: Reply message
-/// - `Err(code)`: Error code
+/// - `Err(E_TIMEDOUT)`: No reply in time
+/// - `Err(E_PEERDIED)`: The service died before replying
+/// - `Err(E_BUSY)`: Another call is in progress
+/// - `Err(code)`: The request could not be sent
 #[cfg(target_arch = "wasm32")]
-pub fn call(endpoint_slot: u32, tag: u32, data: &[u8]) -> Result<ReceivedMessage, u32> {
-    // Simple implementation: send then poll for reply
-    send(endpoint_slot, tag, data)?;
+pub fn call_timeout(
+    endpoint_slot: u32,
+    tag: u32,
+    data: &[u8],
+    timeout_ms: u32,
+) -> Result<ReceivedMessage, u32> {
+    use crate::SYS_CALL_WAIT;
+    use zos_ipc::syscall_error;
 
-    // Poll for reply (would block in a real implementation)
+    // Send the request, waiting for room if the endpoint is full
     loop {
-        if let Ok(msg) = receive(endpoint_slot) {
-            return Ok(msg);
+        let result = unsafe {
+            zos_send_bytes(data.as_ptr(), data.len() as u32);
+            zos_syscall(SYS_CALL, endpoint_slot, tag, timeout_ms) as i32
+        };
+        match result {
+            0 => break,
+            syscall_error::WOULD_BLOCK => yield_now(),
+            syscall_error::BUSY => return Err(error::E_BUSY),
+            e => return Err(e as u32),
+        }
+    }
+
+    // Parked until the call ends; the kernel enforces the timeout
+    let mut buffer = [0u8; 16384];
+    loop {
+        let result = unsafe { zos_syscall(SYS_CALL_WAIT, 0, 0, 0) as i32 };
+        match result {
+            0 => yield_now(),
+            1 => {
+                let len = unsafe { zos_recv_bytes(buffer.as_mut_ptr(), buffer.len() as u32) };
+                let frame = zos_ipc::frame::Frame::decode(&buffer[..len as usize])
+                    .map_err(|_| error::E_INVAL)?;
+                return Ok(ReceivedMessage {
+                    from_pid: frame.from_pid,
+                    tag: frame.tag,
+                    cap_slots: frame.cap_slots,
+                    correlation_id: frame.correlation_id,
+                    data: crate::pool::copy_of(frame.data),
+                });
+            }
+            syscall_error::TIMED_OUT => return Err(error::E_TIMEDOUT),
+            syscall_error::PEER_DIED => return Err(error::E_PEERDIED),
+            _ => return Err(error::E_NOENT),
         }
-        yield_now();
     }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn call(_endpoint_slot: u32, _tag: u32, _data: &[u8]) -> Result<ReceivedMessage, u32> {
+pub fn call_timeout(
+    _endpoint_slot: u32,
+    _tag: u32,
+    _data: &[u8],
+    _timeout_ms: u32,
+) -> Result<ReceivedMessage, u32> {
     Err(error::E_NOSYS)
 }
 
 /// Reply to a call
 ///
+/// Only the process that received the call's request may reply, and only
+/// once; the reply fails with `syscall_error::NOT_FOUND` otherwise, or once
+/// the caller has stopped waiting.
+///
 /// # Arguments
 /// - `caller_pid`: PID of the calling process
 /// - `tag`: Reply message tag
@@ -425,6 +821,177 @@ pub fn reply(_caller_pid: u32, _tag: u32, _data: &[u8]) -> Result<(), u32> {
     Err(error::E_NOSYS)
 }
 
+// ============================================================================
+// Notification Syscalls
+// ============================================================================
+//
+// A notification carries 31 signal bits (`NOTIFY_BITS`) and no data. Signals OR into the
+// pending bits and a wait takes them all, so a bit signalled twice before
+// a wait is seen once. Signalling never allocates and never blocks.
+
+/// Create a notification.
+///
+/// # Returns
+/// - `Ok(slot)`: Slot of a Notification capability with full permissions
+/// - `Err(code)`: Error code (`syscall_error::LIMIT_EXCEEDED` at the
+///   per-process limit)
+#[cfg(target_arch = "wasm32")]
+pub fn notify_create() -> Result<u32, u32> {
+    use crate::SYS_NOTIFY_CREATE;
+
+    let result = unsafe { zos_syscall(SYS_NOTIFY_CREATE, 0, 0, 0) };
+    if result >= 0 {
+        Ok(result as u32)
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn notify_create() -> Result<u32, u32> {
+    Err(error::E_NOSYS)
+}
+
+/// OR `bits` (non-zero, within `NOTIFY_BITS`) into the pending bits of the notification in
+/// `slot`. Needs write permission.
+#[cfg(target_arch = "wasm32")]
+pub fn signal(slot: u32, bits: u32) -> Result<(), u32> {
+    use crate::SYS_SIGNAL;
+
+    let result = unsafe { zos_syscall(SYS_SIGNAL, slot, bits, 0) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn signal(_slot: u32, _bits: u32) -> Result<(), u32> {
+    Err(error::E_NOSYS)
+}
+
+/// Take the pending bits of the notification in `slot`, without waiting.
+/// Needs read permission.
+///
+/// # Returns
+/// - `Ok(bits)`: The bits signalled since the last wait (0 if none)
+/// - `Err(code)`: Error code
+#[cfg(target_arch = "wasm32")]
+pub fn poll_notification(slot: u32) -> Result<u32, u32> {
+    use crate::SYS_WAIT;
+
+    let result = unsafe { zos_syscall(SYS_WAIT, slot, 0, 0) };
+    if result >= 0 {
+        Ok(result as u32)
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn poll_notification(_slot: u32) -> Result<u32, u32> {
+    Err(error::E_NOSYS)
+}
+
+/// Wait for the notification in `slot` to be signalled, then take its
+/// pending bits.
+///
+/// This polls [`poll_notification`] in a loop, yielding between attempts.
+pub fn wait_notification(slot: u32) -> Result<u32, u32> {
+    loop {
+        match poll_notification(slot)? {
+            0 => yield_now(),
+            bits => return Ok(bits),
+        }
+    }
+}
+
+// ============================================================================
+// Broadcast Syscalls
+// ============================================================================
+//
+// A broadcast endpoint announces events to many listeners. Each listener
+// subscribes one of its own endpoints and receives published messages there
+// like any other message. Publishing never blocks: a listener that has
+// fallen `MAX_BROADCAST_BACKLOG` messages behind misses new ones until it
+// catches up.
+
+/// Create a broadcast endpoint.
+///
+/// # Returns
+/// - `Ok(slot)`: Slot of an Endpoint capability with full permissions
+/// - `Err(code)`: Error code (`syscall_error::LIMIT_EXCEEDED` at the
+///   per-process limit)
+#[cfg(target_arch = "wasm32")]
+pub fn broadcast_create() -> Result<u32, u32> {
+    use crate::SYS_BROADCAST_CREATE;
+
+    let result = unsafe { zos_syscall(SYS_BROADCAST_CREATE, 0, 0, 0) };
+    if result >= 0 {
+        Ok(result as u32)
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn broadcast_create() -> Result<u32, u32> {
+    Err(error::E_NOSYS)
+}
+
+/// Have messages published to the broadcast endpoint in `broadcast_slot`
+/// queued on the endpoint in `endpoint_slot`, instead of wherever they
+/// went before. Needs read permission on both.
+#[cfg(target_arch = "wasm32")]
+pub fn subscribe(broadcast_slot: u32, endpoint_slot: u32) -> Result<(), u32> {
+    use crate::SYS_SUBSCRIBE;
+
+    let result = unsafe { zos_syscall(SYS_SUBSCRIBE, broadcast_slot, endpoint_slot, 0) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn subscribe(_broadcast_slot: u32, _endpoint_slot: u32) -> Result<(), u32> {
+    Err(error::E_NOSYS)
+}
+
+/// Stop receiving the messages published to the broadcast endpoint in
+/// `broadcast_slot`. Those already queued stay queued.
+pub fn unsubscribe(broadcast_slot: u32) -> Result<(), u32> {
+    subscribe(broadcast_slot, crate::UNSUBSCRIBE)
+}
+
+/// Publish a message to every subscriber of the broadcast endpoint in
+/// `broadcast_slot`. Needs write permission.
+///
+/// # Returns
+/// - `Ok(count)`: Number of subscribers the message was queued for
+/// - `Err(code)`: Error code
+#[cfg(target_arch = "wasm32")]
+pub fn publish(broadcast_slot: u32, tag: u32, data: &[u8]) -> Result<u32, u32> {
+    use crate::SYS_PUBLISH;
+
+    let result = unsafe {
+        zos_send_bytes(data.as_ptr(), data.len() as u32);
+        zos_syscall(SYS_PUBLISH, broadcast_slot, tag, data.len() as u32)
+    };
+    if result >= 0 {
+        Ok(result as u32)
+    } else {
+        Err(result as u32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn publish(_broadcast_slot: u32, _tag: u32, _data: &[u8]) -> Result<u32, u32> {
+    Err(error::E_NOSYS)
+}
+
 // ============================================================================
 // Capability Syscalls
 // ============================================================================
@@ -652,12 +1219,20 @@ pub fn create_endpoint() -> Result<(u64, u32), u32> {
 /// # Returns
 /// - `Ok(pid)`: The PID assigned to the new process
 /// - `Err(code)`: Error code (e.g., permission denied if caller is not Init)
-#[cfg(target_arch = "wasm32")]
 pub fn register_process(name: &str) -> Result<u32, u32> {
+    register_process_in_group(name, 0)
+}
+
+/// Register a new process in a process group (Init-only syscall).
+///
+/// Like `register_process`, but the process joins group `pgid` if it has
+/// members left; with 0 it leads a group of its own.
+#[cfg(target_arch = "wasm32")]
+pub fn register_process_in_group(name: &str, pgid: u32) -> Result<u32, u32> {
     let bytes = name.as_bytes();
     unsafe {
         zos_send_bytes(bytes.as_ptr(), bytes.len() as u32);
-        let result = zos_syscall(SYS_REGISTER_PROCESS, bytes.len() as u32, 0, 0) as i32;
+        let result = zos_syscall(SYS_REGISTER_PROCESS, bytes.len() as u32, pgid, 0) as i32;
         if result >= 0 {
             Ok(result as u32)
         } else {
@@ -667,7 +1242,7 @@ pub fn register_process(name: &str) -> Result<u32, u32> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn register_process(_name: &str) -> Result<u32, u32> {
+pub fn register_process_in_group(_name: &str, _pgid: u32) -> Result<u32, u32> {
     Err(error::E_NOSYS)
 }
 
@@ -806,6 +1381,39 @@ pub fn spawn_process(_name: &str, _binary: &[u8]) -> Result<u32, i32> {
     Err(-3)
 }
 
+/// Bind a service name to a process's input endpoint (Init-only syscall).
+///
+/// Processes sending with [`send_named`] reach the bound process; rebinding
+/// a restarted service moves them to the new instance.
+///
+/// # Returns
+/// - `Ok(generation)`: The binding's generation
+/// - `Err(code)`: Error code
+///   - `PERMISSION_DENIED (-4)`: Caller is not Init
+///   - `NOT_FOUND (-2)`: No such process
+///   - `INVALID_ARGUMENT (-5)`: Bad name or the process has no input endpoint
+#[cfg(target_arch = "wasm32")]
+pub fn bind_name(name: &str, pid: u32) -> Result<u64, i32> {
+    use crate::SYS_BIND_NAME;
+
+    let name = name.as_bytes();
+    unsafe {
+        zos_send_bytes(name.as_ptr(), name.len() as u32);
+        let result = zos_syscall(SYS_BIND_NAME, pid, name.len() as u32, 0);
+        if result < 0 {
+            Err(result as i32)
+        } else {
+            Ok(result as u64)
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn bind_name(_name: &str, _pid: u32) -> Result<u64, i32> {
+    // NOT_SUPPORTED error code
+    Err(-3)
+}
+
 // ============================================================================
 // Introspection Syscalls
 // ============================================================================
@@ -921,3 +1529,22 @@ pub fn list_processes() -> Vec<ProcessInfo> {
 pub fn list_processes() -> Vec<ProcessInfo> {
     Vec::new()
 }
+
+/// List all processes with their usage figures (CPU time, syscalls, IPC)
+#[cfg(target_arch = "wasm32")]
+pub fn process_usage() -> Vec<ProcessUsage> {
+    let mut buffer = [0u8; 8192];
+    unsafe {
+        let result = zos_syscall(SYS_PS, PS_USAGE, 0, 0);
+        if result != 0 {
+            return Vec::new();
+        }
+        let len = zos_recv_bytes(buffer.as_mut_ptr(), buffer.len() as u32) as usize;
+        ProcessUsage::parse_list(&buffer[..len.min(buffer.len())])
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn process_usage() -> Vec<ProcessUsage> {
+    Vec::new()
+}
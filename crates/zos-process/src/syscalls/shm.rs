@@ -0,0 +1,187 @@
+//! Shared memory syscalls for Zero OS
+//!
+//! A region is created with [`shm_create`] and reached through its Memory
+//! capability, which can be granted or sent with [`send_with_caps`] like
+//! any other. Mapping it gives this process a window onto the region;
+//! [`SharedMemory::flush`] copies the window into the region and
+//! [`SharedMemory::fetch`] copies the region into the window, so bulk data
+//! never travels in an IPC message.
+//!
+//! [`send_with_caps`]: crate::send_with_caps
+
+use alloc::boxed::Box;
+use alloc::vec;
+
+#[allow(unused_imports)]
+use crate::{SYS_SHM_CREATE, SYS_SHM_FETCH, SYS_SHM_FLUSH, SYS_SHM_MAP, SYS_SHM_UNMAP};
+#[cfg(not(target_arch = "wasm32"))]
+use zos_ipc::syscall_error::NOT_SUPPORTED;
+#[cfg(target_arch = "wasm32")]
+use zos_ipc::syscall_error::TRANSIENT;
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn zos_syscall(syscall_num: u32, arg1: u32, arg2: u32, arg3: u32) -> i64;
+}
+
+/// Create a zeroed region of `size` bytes.
+///
+/// # Returns
+/// - `Ok(slot)`: Slot of a Memory capability with full permissions
+/// - `Err(code)`: `syscall_error::INVALID_ARGUMENT` or `LIMIT_EXCEEDED`
+#[cfg(target_arch = "wasm32")]
+pub fn shm_create(size: u32) -> Result<u32, i32> {
+    let result = unsafe { zos_syscall(SYS_SHM_CREATE, size, 0, 0) };
+    if result >= 0 {
+        Ok(result as u32)
+    } else {
+        Err(result as i32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shm_create(_size: u32) -> Result<u32, i32> {
+    Err(NOT_SUPPORTED)
+}
+
+/// Map the region of the Memory capability in `slot` at `addr`, up to
+/// `len` bytes. Prefer [`SharedMemory::map`], which owns the window.
+///
+/// # Returns
+/// - `Ok(len)`: Mapped length, at most the region size
+/// - `Err(code)`: Negative `syscall_error`
+#[cfg(target_arch = "wasm32")]
+pub fn shm_map(slot: u32, addr: u32, len: u32) -> Result<u32, i32> {
+    let result = unsafe { zos_syscall(SYS_SHM_MAP, slot, addr, len) };
+    if result >= 0 {
+        Ok(result as u32)
+    } else {
+        Err(result as i32)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shm_map(_slot: u32, _addr: u32, _len: u32) -> Result<u32, i32> {
+    Err(NOT_SUPPORTED)
+}
+
+/// Remove the mapping at `addr`.
+#[cfg(target_arch = "wasm32")]
+pub fn shm_unmap(addr: u32) -> Result<(), i32> {
+    check(unsafe { zos_syscall(SYS_SHM_UNMAP, addr, 0, 0) })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shm_unmap(_addr: u32) -> Result<(), i32> {
+    Err(NOT_SUPPORTED)
+}
+
+/// Copy `len` bytes at `offset` of the mapping at `addr` into the region.
+#[cfg(target_arch = "wasm32")]
+pub fn shm_flush(addr: u32, offset: u32, len: u32) -> Result<(), i32> {
+    copy(SYS_SHM_FLUSH, addr, offset, len)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shm_flush(_addr: u32, _offset: u32, _len: u32) -> Result<(), i32> {
+    Err(NOT_SUPPORTED)
+}
+
+/// Copy `len` bytes at `offset` of the region into the mapping at `addr`.
+#[cfg(target_arch = "wasm32")]
+pub fn shm_fetch(addr: u32, offset: u32, len: u32) -> Result<(), i32> {
+    copy(SYS_SHM_FETCH, addr, offset, len)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shm_fetch(_addr: u32, _offset: u32, _len: u32) -> Result<(), i32> {
+    Err(NOT_SUPPORTED)
+}
+
+/// Flush or fetch, retrying a few times while the copy fails transiently
+/// (the host has not yet seen this process's memory grow).
+#[cfg(target_arch = "wasm32")]
+fn copy(syscall_num: u32, addr: u32, offset: u32, len: u32) -> Result<(), i32> {
+    const ATTEMPTS: usize = 8;
+    let mut result = Err(TRANSIENT);
+    for _ in 0..ATTEMPTS {
+        result = check(unsafe { zos_syscall(syscall_num, addr, offset, len) });
+        if result != Err(TRANSIENT) {
+            break;
+        }
+        crate::yield_now();
+    }
+    result
+}
+
+#[cfg(target_arch = "wasm32")]
+fn check(result: i64) -> Result<(), i32> {
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(result as i32)
+    }
+}
+
+/// A region mapped into this process, owning the window it is mapped at.
+///
+/// The window is unmapped when this is dropped. It stays mapped after the
+/// capability it was mapped with has been sent on.
+pub struct SharedMemory {
+    window: Box<[u8]>,
+    /// Mapped length, at most the window's
+    len: usize,
+}
+
+impl SharedMemory {
+    /// Map up to `len` bytes of the region of the Memory capability in
+    /// `slot`, in a fresh window.
+    pub fn map(slot: u32, len: u32) -> Result<Self, i32> {
+        let mut window = vec![0u8; len as usize].into_boxed_slice();
+        let mapped = shm_map(slot, window.as_mut_ptr() as u32, len)?;
+        Ok(Self {
+            window,
+            len: mapped as usize,
+        })
+    }
+
+    /// Mapped length: the first `len()` bytes of the region.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether nothing is mapped (never, once mapped).
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The window, as of the last fetch and local writes.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.window[..self.len]
+    }
+
+    /// The window, to fill before a flush.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.window[..self.len]
+    }
+
+    /// Copy `len` bytes at `offset` of the window into the region.
+    pub fn flush(&self, offset: usize, len: usize) -> Result<(), i32> {
+        shm_flush(self.addr(), offset as u32, len as u32)
+    }
+
+    /// Copy `len` bytes at `offset` of the region into the window.
+    pub fn fetch(&mut self, offset: usize, len: usize) -> Result<(), i32> {
+        shm_fetch(self.addr(), offset as u32, len as u32)
+    }
+
+    fn addr(&self) -> u32 {
+        self.window.as_ptr() as u32
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        let _ = shm_unmap(self.addr());
+    }
+}
@@ -9,8 +9,8 @@
 use crate::error;
 #[allow(unused_imports)]
 use crate::{
-    SYS_STORAGE_BATCH_WRITE, SYS_STORAGE_DELETE, SYS_STORAGE_EXISTS, SYS_STORAGE_LIST,
-    SYS_STORAGE_READ, SYS_STORAGE_WRITE,
+    SYS_STORAGE_BATCH_WRITE, SYS_STORAGE_CANCEL, SYS_STORAGE_DELETE, SYS_STORAGE_EXISTS,
+    SYS_STORAGE_LIST, SYS_STORAGE_READ, SYS_STORAGE_WRITE,
 };
 #[allow(unused_imports)]
 use alloc::vec::Vec;
@@ -37,13 +37,13 @@ extern "C" {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn storage_read_async(key: &str) -> Result<i64, i64> {
+pub fn storage_read_async(key: &str) -> Result<u32, i64> {
     let key_bytes = key.as_bytes();
     unsafe {
         zos_send_bytes(key_bytes.as_ptr(), key_bytes.len() as u32);
         let result = zos_syscall(SYS_STORAGE_READ, key_bytes.len() as u32, 0, 0);
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -51,7 +51,7 @@ pub fn storage_read_async(key: &str) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn storage_read_async(_key: &str) -> Result<i64, i64> {
+pub fn storage_read_async(_key: &str) -> Result<u32, i64> {
     Err(error::E_NOSYS as i64)
 }
 
@@ -68,7 +68,7 @@ pub fn storage_read_async(_key: &str) -> Result<i64, i64> {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn storage_write_async(key: &str, value: &[u8]) -> Result<i64, i64> {
+pub fn storage_write_async(key: &str, value: &[u8]) -> Result<u32, i64> {
     let key_bytes = key.as_bytes();
     // Data format: [key_len: u32, key: [u8], value: [u8]]
     let mut data = Vec::with_capacity(4 + key_bytes.len() + value.len());
@@ -85,7 +85,7 @@ pub fn storage_write_async(key: &str, value: &[u8]) -> Result<i64, i64> {
             0,
         );
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -93,7 +93,7 @@ pub fn storage_write_async(key: &str, value: &[u8]) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn storage_write_async(_key: &str, _value: &[u8]) -> Result<i64, i64> {
+pub fn storage_write_async(_key: &str, _value: &[u8]) -> Result<u32, i64> {
     Err(error::E_NOSYS as i64)
 }
 
@@ -109,13 +109,13 @@ pub fn storage_write_async(_key: &str, _value: &[u8]) -> Result<i64, i64> {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn storage_delete_async(key: &str) -> Result<i64, i64> {
+pub fn storage_delete_async(key: &str) -> Result<u32, i64> {
     let key_bytes = key.as_bytes();
     unsafe {
         zos_send_bytes(key_bytes.as_ptr(), key_bytes.len() as u32);
         let result = zos_syscall(SYS_STORAGE_DELETE, key_bytes.len() as u32, 0, 0);
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -123,7 +123,7 @@ pub fn storage_delete_async(key: &str) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn storage_delete_async(_key: &str) -> Result<i64, i64> {
+pub fn storage_delete_async(_key: &str) -> Result<u32, i64> {
     Err(error::E_NOSYS as i64)
 }
 
@@ -140,13 +140,13 @@ pub fn storage_delete_async(_key: &str) -> Result<i64, i64> {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn storage_list_async(prefix: &str) -> Result<i64, i64> {
+pub fn storage_list_async(prefix: &str) -> Result<u32, i64> {
     let prefix_bytes = prefix.as_bytes();
     unsafe {
         zos_send_bytes(prefix_bytes.as_ptr(), prefix_bytes.len() as u32);
         let result = zos_syscall(SYS_STORAGE_LIST, prefix_bytes.len() as u32, 0, 0);
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -154,7 +154,7 @@ pub fn storage_list_async(prefix: &str) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn storage_list_async(_prefix: &str) -> Result<i64, i64> {
+pub fn storage_list_async(_prefix: &str) -> Result<u32, i64> {
     Err(error::E_NOSYS as i64)
 }
 
@@ -171,13 +171,13 @@ pub fn storage_list_async(_prefix: &str) -> Result<i64, i64> {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn storage_exists_async(key: &str) -> Result<i64, i64> {
+pub fn storage_exists_async(key: &str) -> Result<u32, i64> {
     let key_bytes = key.as_bytes();
     unsafe {
         zos_send_bytes(key_bytes.as_ptr(), key_bytes.len() as u32);
         let result = zos_syscall(SYS_STORAGE_EXISTS, key_bytes.len() as u32, 0, 0);
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -185,7 +185,7 @@ pub fn storage_exists_async(key: &str) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn storage_exists_async(_key: &str) -> Result<i64, i64> {
+pub fn storage_exists_async(_key: &str) -> Result<u32, i64> {
     Err(error::E_NOSYS as i64)
 }
 
@@ -202,7 +202,7 @@ pub fn storage_exists_async(_key: &str) -> Result<i64, i64> {
 /// - `Ok(request_id)`: Request ID to match with result
 /// - `Err(code)`: Failed to start operation
 #[cfg(target_arch = "wasm32")]
-pub fn storage_batch_write_async(items: &[(&str, &[u8])]) -> Result<i64, i64> {
+pub fn storage_batch_write_async(items: &[(&str, &[u8])]) -> Result<u32, i64> {
     // Data format: [count: u32, (key_len: u32, key: [u8], value_len: u32, value: [u8])*]
     let mut data = Vec::new();
     data.extend_from_slice(&(items.len() as u32).to_le_bytes());
@@ -224,7 +224,7 @@ pub fn storage_batch_write_async(items: &[(&str, &[u8])]) -> Result<i64, i64> {
             0,
         );
         if result >= 0 {
-            Ok(result)
+            Ok(result as u32)
         } else {
             Err(result)
         }
@@ -232,6 +232,34 @@ pub fn storage_batch_write_async(items: &[(&str, &[u8])]) -> Result<i64, i64> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn storage_batch_write_async(_items: &[(&str, &[u8])]) -> Result<i64, i64> {
+pub fn storage_batch_write_async(_items: &[(&str, &[u8])]) -> Result<u32, i64> {
+    Err(error::E_NOSYS as i64)
+}
+
+/// Cancel a pending async storage operation.
+///
+/// The operation still completes with a MSG_STORAGE_RESULT, carrying the
+/// CANCELLED result type if the platform aborted it in time.
+///
+/// # Arguments
+/// - `request_id`: Request ID returned when the operation was started
+///
+/// # Returns
+/// - `Ok(())`: Cancellation requested
+/// - `Err(code)`: Unknown request, or not started by this process
+#[cfg(target_arch = "wasm32")]
+pub fn storage_cancel(request_id: u32) -> Result<(), i64> {
+    unsafe {
+        let result = zos_syscall(SYS_STORAGE_CANCEL, request_id, 0, 0);
+        if result >= 0 {
+            Ok(())
+        } else {
+            Err(result)
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn storage_cancel(_request_id: u32) -> Result<(), i64> {
     Err(error::E_NOSYS as i64)
 }
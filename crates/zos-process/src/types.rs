@@ -91,6 +91,8 @@ pub struct ReceivedMessage {
     /// These are slots in the receiver's CSpace where the kernel installed
     /// capabilities that were transferred with this message.
     pub cap_slots: Vec<u32>,
+    /// Correlation ID the sender set; a response carries its request's
+    pub correlation_id: Option<u32>,
     /// Message data
     pub data: Vec<u8>,
 }
@@ -117,3 +119,203 @@ pub struct ProcessInfo {
     pub name: String,
     pub state: u8,
 }
+
+/// A process with its usage figures, returned from process_usage
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub name: String,
+    pub state: u8,
+    /// Scheduling class (see `zos_ipc::priority`)
+    pub priority: u8,
+    pub memory_size: u64,
+    pub syscall_count: u64,
+    /// IPC messages sent
+    pub ipc_sent: u64,
+    /// IPC messages received
+    pub ipc_received: u64,
+    /// Time spent running between syscalls, in nanoseconds
+    pub run_time_ns: u64,
+    /// Times the process resumed after waiting
+    pub wakeups: u64,
+}
+
+impl ProcessUsage {
+    /// Bytes following the name in each `SYS_PS` record with `PS_USAGE`
+    const USAGE_LEN: usize = 2 + 6 * 8;
+
+    /// Parse a `SYS_PS` response requested with `PS_USAGE`.
+    ///
+    /// Stops at the first truncated record.
+    pub fn parse_list(bytes: &[u8]) -> Vec<ProcessUsage> {
+        let Some(count) = bytes.get(..4) else {
+            return Vec::new();
+        };
+        let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize;
+        let mut procs = Vec::with_capacity(count.min(64));
+        let mut rest = &bytes[4..];
+        for _ in 0..count {
+            if rest.len() < 6 {
+                break;
+            }
+            let pid = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+            let name_len = u16::from_le_bytes([rest[4], rest[5]]) as usize;
+            rest = &rest[6..];
+            if rest.len() < name_len + Self::USAGE_LEN {
+                break;
+            }
+            let name = String::from_utf8_lossy(&rest[..name_len]).into_owned();
+            let usage = &rest[name_len..name_len + Self::USAGE_LEN];
+            let field = |i: usize| {
+                let at = 2 + i * 8;
+                let mut word = [0u8; 8];
+                word.copy_from_slice(&usage[at..at + 8]);
+                u64::from_le_bytes(word)
+            };
+            procs.push(ProcessUsage {
+                pid,
+                name,
+                state: usage[0],
+                priority: usage[1],
+                memory_size: field(0),
+                syscall_count: field(1),
+                ipc_sent: field(2),
+                ipc_received: field(3),
+                run_time_ns: field(4),
+                wakeups: field(5),
+            });
+            rest = &rest[name_len + Self::USAGE_LEN..];
+        }
+        procs
+    }
+}
+
+/// Another process's runtime state, returned from proc_state
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessSnapshot {
+    pub name: String,
+    pub state: u8,
+    /// Scheduling class (see `zos_ipc::priority`)
+    pub priority: u8,
+    /// Whether its last syscall left it waiting for a message, signal or
+    /// reply
+    pub waiting: bool,
+    /// Number of its last syscall
+    pub last_syscall: u32,
+    pub memory_size: u64,
+    pub memory_limit: u64,
+    pub syscall_count: u64,
+    /// IPC messages sent
+    pub ipc_sent: u64,
+    /// IPC messages received
+    pub ipc_received: u64,
+    /// Time spent running between syscalls, in nanoseconds
+    pub run_time_ns: u64,
+    /// Times the process resumed after waiting
+    pub wakeups: u64,
+    /// When it last made a syscall, in nanoseconds since boot
+    pub last_active_ns: u64,
+    pub capabilities: u32,
+    /// Endpoints it owns
+    pub endpoints: u32,
+    /// Messages queued on the endpoints it owns
+    pub queued_messages: u32,
+}
+
+impl ProcessSnapshot {
+    /// Bytes before the name in a `SYS_PROC_STATE` response
+    const FIXED_LEN: usize = 3 + 4 + 8 * 8 + 3 * 4;
+
+    /// Parse a `SYS_PROC_STATE` response.
+    pub fn parse(bytes: &[u8]) -> Option<ProcessSnapshot> {
+        if bytes.len() < Self::FIXED_LEN {
+            return None;
+        }
+        let word = |at: usize| {
+            u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+        let field = |i: usize| {
+            let at = 7 + i * 8;
+            let mut value = [0u8; 8];
+            value.copy_from_slice(&bytes[at..at + 8]);
+            u64::from_le_bytes(value)
+        };
+        Some(ProcessSnapshot {
+            name: String::from_utf8_lossy(&bytes[Self::FIXED_LEN..]).into_owned(),
+            state: bytes[0],
+            priority: bytes[1],
+            waiting: bytes[2] != 0,
+            last_syscall: word(3),
+            memory_size: field(0),
+            memory_limit: field(1),
+            syscall_count: field(2),
+            ipc_sent: field(3),
+            ipc_received: field(4),
+            run_time_ns: field(5),
+            wakeups: field(6),
+            last_active_ns: field(7),
+            capabilities: word(71),
+            endpoints: word(75),
+            queued_messages: word(79),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_process_usage() {
+        let mut bytes = 2u32.to_le_bytes().to_vec();
+        for (pid, name, run_time) in [(1u32, "init", 500u64), (7, "terminal", 9_000)] {
+            bytes.extend_from_slice(&pid.to_le_bytes());
+            bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(&[0, 1]);
+            for value in [4096u64, 12, 3, 4, run_time, 2] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        let procs = ProcessUsage::parse_list(&bytes);
+        assert_eq!(procs.len(), 2);
+        assert_eq!(procs[1].pid, 7);
+        assert_eq!(procs[1].name, "terminal");
+        assert_eq!(procs[1].priority, 1);
+        assert_eq!(procs[1].memory_size, 4096);
+        assert_eq!(procs[1].ipc_received, 4);
+        assert_eq!(procs[1].run_time_ns, 9_000);
+        assert_eq!(procs[1].wakeups, 2);
+
+        // A truncated record is dropped
+        assert_eq!(ProcessUsage::parse_list(&bytes[..bytes.len() - 1]).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_process_snapshot() {
+        let mut bytes = alloc::vec![1, 2, 1];
+        bytes.extend_from_slice(&0x41u32.to_le_bytes());
+        for value in [65536u64, 1 << 20, 40, 5, 6, 7_000, 3, 123_456] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for count in [4u32, 1, 9] {
+            bytes.extend_from_slice(&count.to_le_bytes());
+        }
+        bytes.extend_from_slice(b"vfs");
+
+        let snapshot = ProcessSnapshot::parse(&bytes).unwrap();
+        assert_eq!(snapshot.name, "vfs");
+        assert!(snapshot.waiting);
+        assert_eq!(snapshot.last_syscall, 0x41);
+        assert_eq!(snapshot.memory_limit, 1 << 20);
+        assert_eq!(snapshot.last_active_ns, 123_456);
+        assert_eq!(snapshot.capabilities, 4);
+        assert_eq!(snapshot.queued_messages, 9);
+
+        assert_eq!(
+            ProcessSnapshot::parse(&bytes[..ProcessSnapshot::FIXED_LEN - 1]),
+            None
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! Archive Service entry point
+//!
+//! Thin wrapper that invokes the Archive Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::ArchiveService;
+
+app_main!(ArchiveService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("ArchiveService is meant to run as WASM in Zero OS");
+}
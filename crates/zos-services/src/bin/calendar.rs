@@ -0,0 +1,17 @@
+//! Calendar Service entry point
+//!
+//! Thin wrapper that invokes the Calendar Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::CalendarService;
+
+app_main!(CalendarService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("CalendarService is meant to run as WASM in Zero OS");
+}
@@ -0,0 +1,17 @@
+//! Clipboard Service entry point
+//!
+//! Thin wrapper that invokes the Clipboard Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::ClipboardService;
+
+app_main!(ClipboardService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("ClipboardService is meant to run as WASM in Zero OS");
+}
@@ -0,0 +1,17 @@
+//! Command Service entry point
+//!
+//! Thin wrapper that invokes the Command Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::CommandService;
+
+app_main!(CommandService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("CommandService is meant to run as WASM in Zero OS");
+}
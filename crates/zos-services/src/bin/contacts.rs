@@ -0,0 +1,17 @@
+//! Contacts Service entry point
+//!
+//! Thin wrapper that invokes the Address Book Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::AddressBookService;
+
+app_main!(AddressBookService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("AddressBookService is meant to run as WASM in Zero OS");
+}
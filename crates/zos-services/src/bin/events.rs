@@ -0,0 +1,17 @@
+//! Event Bus Service entry point
+//!
+//! Thin wrapper that invokes the Event Bus Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::EventBusService;
+
+app_main!(EventBusService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("EventBusService is meant to run as WASM in Zero OS");
+}
@@ -0,0 +1,17 @@
+//! Feature Flag Service entry point
+//!
+//! Thin wrapper that invokes the Feature Flag Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::FeatureFlagService;
+
+app_main!(FeatureFlagService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("FeatureFlagService is meant to run as WASM in Zero OS");
+}
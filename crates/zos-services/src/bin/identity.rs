@@ -6,6 +6,9 @@
 
 extern crate alloc;
 
+// Runs for the life of the system, so it needs an allocator that frees
+zos_allocator::init!(4 * 1024 * 1024, BuddyAllocator);
+
 use zos_services::services::IdentityService;
 use zos_apps::app_main;
 
@@ -0,0 +1,17 @@
+//! Messaging Service entry point
+//!
+//! Thin wrapper that invokes the Messaging Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::MessagingService;
+
+app_main!(MessagingService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("MessagingService is meant to run as WASM in Zero OS");
+}
@@ -0,0 +1,17 @@
+//! Print Service entry point
+//!
+//! Thin wrapper that invokes the Print Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::PrintService;
+
+app_main!(PrintService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("PrintService is meant to run as WASM in Zero OS");
+}
@@ -0,0 +1,17 @@
+//! Speech Service entry point
+//!
+//! Thin wrapper that invokes the Speech Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::SpeechService;
+
+app_main!(SpeechService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("SpeechService is meant to run as WASM in Zero OS");
+}
@@ -0,0 +1,17 @@
+//! Thumbnail Service entry point
+//!
+//! Thin wrapper that invokes the Thumbnail Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::ThumbnailService;
+
+app_main!(ThumbnailService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("ThumbnailService is meant to run as WASM in Zero OS");
+}
@@ -0,0 +1,17 @@
+//! Update Service entry point
+//!
+//! Thin wrapper that invokes the Update Service from the library.
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+extern crate alloc;
+
+use zos_apps::app_main;
+use zos_services::services::UpdateService;
+
+app_main!(UpdateService);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    println!("UpdateService is meant to run as WASM in Zero OS");
+}
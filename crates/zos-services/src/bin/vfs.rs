@@ -6,6 +6,9 @@
 
 extern crate alloc;
 
+// Runs for the life of the system, so it needs an allocator that frees
+zos_allocator::init!(8 * 1024 * 1024, BuddyAllocator);
+
 use zos_services::services::VfsService;
 use zos_apps::app_main;
 
@@ -7,6 +7,18 @@
 //! - **Time Service**: System time and timezone management
 //! - **Network Service**: Network connectivity and operations
 //! - **Permission Service**: Permission management for apps
+//! - **Update Service**: Signed A/B system updates with automatic rollback
+//! - **Feature Flag Service**: Runtime toggles for risky subsystems
+//! - **Speech Service**: Text output channel for the screen reader
+//! - **Event Bus Service**: Topic-based publish/subscribe between services
+//! - **Calendar Service**: Calendar events shared by the Calendar app and widgets
+//! - **Contacts Service**: Address book of contacts with verified identity keys
+//! - **Messaging Service**: End-to-end encrypted messages between machines and contacts
+//! - **Clipboard Service**: The clipboard, its history and opt-in sync between machines
+//! - **Print Service**: Print jobs to the print dialog or to PDF files
+//! - **Thumbnail Service**: Cached thumbnails of image files
+//! - **Archive Service**: Zip and tar archives streamed through the VFS
+//! - **Command Service**: App commands behind the system-wide command palette
 //!
 //! These services run as background processes in Zero OS and provide
 //! core functionality that apps depend on.
@@ -19,11 +31,19 @@
 //!
 //! Binary entry points are thin wrappers in `src/bin/` that just invoke
 //! the `app_main!` macro with the service type.
+//!
+//! The `response` module sends services' JSON responses to their clients.
+//! The `signing` module holds the service keys and signed request envelope
+//! services use to order privileged operations from each other, and the
+//! `trust` module tells services apart by name rather than by PID.
 
 extern crate alloc;
 
 pub mod manifests;
+pub mod response;
 pub mod services;
+pub mod signing;
+pub mod trust;
 
 #[cfg(test)]
 pub mod test_utils;
@@ -37,10 +57,14 @@ pub use zos_apps::{init, kernel, permission, pm, storage, supervisor};
 // Re-export service manifests for convenience
 pub use manifests::{
     IDENTITY_MANIFEST, NETWORK_MANIFEST, PERMISSION_MANIFEST,
-    TIME_MANIFEST, VFS_MANIFEST, KEYSTORE_MANIFEST,
+    TIME_MANIFEST, VFS_MANIFEST, KEYSTORE_MANIFEST, UPDATE_MANIFEST, FLAGS_MANIFEST,
+    SPEECH_MANIFEST, EVENTS_MANIFEST, CALENDAR_MANIFEST, CONTACTS_MANIFEST,
+    MESSAGING_MANIFEST, CLIPBOARD_MANIFEST, PRINT_MANIFEST, THUMBNAIL_MANIFEST,
+    ARCHIVE_MANIFEST, COMMANDS_MANIFEST,
 };
 
 // Re-export service types for convenience
 pub use services::{
-    IdentityService, NetworkService, PermissionService, TimeService, VfsService,
+    AddressBookService, ArchiveService, CalendarService, ClipboardService, CommandService, EventBusService, FeatureFlagService, IdentityService, MessagingService, NetworkService, PermissionService, PrintService, SpeechService, ThumbnailService, TimeService,
+    UpdateService, VfsService,
 };
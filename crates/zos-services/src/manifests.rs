@@ -37,7 +37,15 @@ pub static PERMISSION_MANIFEST: AppManifest = AppManifest {
             reason: "Root process capability for granting spawn rights",
             required: true,
         },
+        CapabilityRequest {
+            object_type: ObjectType::Filesystem,
+            permissions: Permissions::read_write(),
+            reason: "Order app data deletion through signed VFS requests",
+            required: false,
+        },
     ],
+    commands: &[],
+    memory_limit: None,
 };
 
 /// IdentityService manifest (PID 3)
@@ -66,6 +74,8 @@ pub static IDENTITY_MANIFEST: AppManifest = AppManifest {
             required: true,
         },
     ],
+    commands: &[],
+    memory_limit: None,
 };
 
 /// VFS Service manifest (PID 4)
@@ -94,6 +104,8 @@ pub static VFS_MANIFEST: AppManifest = AppManifest {
             required: true,
         },
     ],
+    commands: &[],
+    memory_limit: None,
 };
 
 /// Time Service manifest (PID 5)
@@ -116,6 +128,8 @@ pub static TIME_MANIFEST: AppManifest = AppManifest {
             required: true,
         },
     ],
+    commands: &[],
+    memory_limit: None,
 };
 
 /// Network Service manifest (PID 8)
@@ -137,7 +151,15 @@ pub static NETWORK_MANIFEST: AppManifest = AppManifest {
             reason: "Perform HTTP requests on behalf of other processes",
             required: true,
         },
+        CapabilityRequest {
+            object_type: ObjectType::Storage,
+            permissions: Permissions::read_write(),
+            reason: "Persist the network policy and response cache to system storage",
+            required: true,
+        },
     ],
+    commands: &[],
+    memory_limit: None,
 };
 
 /// Keystore Service manifest (PID 7)
@@ -160,4 +182,288 @@ pub static KEYSTORE_MANIFEST: AppManifest = AppManifest {
             required: true,
         },
     ],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Update Service manifest
+pub static UPDATE_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.update",
+    name: "Update Service",
+    version: "1.0.0",
+    description: "A/B system update staging and rollback service for Zero OS",
+    capabilities: &[
+        CapabilityRequest {
+            object_type: ObjectType::Endpoint,
+            permissions: Permissions::full(),
+            reason: "Receive update requests and boot health reports",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Network,
+            permissions: Permissions::full(),
+            reason: "Download signed system bundles",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Storage,
+            permissions: Permissions::read_write(),
+            reason: "Stage bundles into system slots and persist slot state",
+            required: true,
+        },
+    ],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Feature Flag Service manifest
+pub static FLAGS_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.flags",
+    name: "Feature Flag Service",
+    version: "1.0.0",
+    description: "Persistent feature flags with per-user overrides for Zero OS",
+    capabilities: &[
+        CapabilityRequest {
+            object_type: ObjectType::Endpoint,
+            permissions: Permissions::full(),
+            reason: "Receive flag queries and changes and send responses",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Storage,
+            permissions: Permissions::read_write(),
+            reason: "Persist feature flags to system storage",
+            required: true,
+        },
+    ],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Speech Service manifest
+pub static SPEECH_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.speech",
+    name: "Speech Service",
+    version: "1.0.0",
+    description: "Screen reader speech output for Zero OS",
+    capabilities: &[CapabilityRequest {
+        object_type: ObjectType::Endpoint,
+        permissions: Permissions::full(),
+        reason: "Receive speech requests and send responses",
+        required: true,
+    }],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Event Bus Service manifest
+pub static EVENTS_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.events",
+    name: "Event Bus Service",
+    version: "1.0.0",
+    description: "Topic-based publish/subscribe for Zero OS",
+    capabilities: &[CapabilityRequest {
+        object_type: ObjectType::Endpoint,
+        permissions: Permissions::full(),
+        reason: "Receive publish and subscribe requests and send responses",
+        required: true,
+    }],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Calendar Service manifest
+pub static CALENDAR_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.calendar",
+    name: "Calendar Service",
+    version: "1.0.0",
+    description: "Shared calendar events and reminders for Zero OS",
+    capabilities: &[
+        CapabilityRequest {
+            object_type: ObjectType::Endpoint,
+            permissions: Permissions::full(),
+            reason: "Receive calendar requests and send responses",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Storage,
+            permissions: Permissions::read_write(),
+            reason: "Persist calendar events and read calendars to import",
+            required: true,
+        },
+    ],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Contacts (address book) Service manifest
+pub static CONTACTS_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.contacts",
+    name: "Contacts Service",
+    version: "1.0.0",
+    description: "Address book of contacts and their verified keys for Zero OS",
+    capabilities: &[
+        CapabilityRequest {
+            object_type: ObjectType::Endpoint,
+            permissions: Permissions::full(),
+            reason: "Receive contacts requests and send responses",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Storage,
+            permissions: Permissions::read_write(),
+            reason: "Persist contacts and read cards to import",
+            required: true,
+        },
+    ],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Messaging Service manifest
+pub static MESSAGING_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.messaging",
+    name: "Messaging Service",
+    version: "1.0.0",
+    description: "End-to-end encrypted messages between machines and contacts for Zero OS",
+    capabilities: &[
+        CapabilityRequest {
+            object_type: ObjectType::Endpoint,
+            permissions: Permissions::full(),
+            reason: "Receive messaging requests and send responses",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Storage,
+            permissions: Permissions::read_write(),
+            reason: "Persist messages and the outbox",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Network,
+            permissions: Permissions::read_write(),
+            reason: "Post and read sealed envelopes on relays",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Keystore,
+            permissions: Permissions::read_only(),
+            reason: "Read this machine's encryption key",
+            required: true,
+        },
+    ],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Clipboard Service manifest
+pub static CLIPBOARD_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.clipboard",
+    name: "Clipboard Service",
+    version: "1.0.0",
+    description: "Clipboard history and opt-in sync between machines for Zero OS",
+    capabilities: &[
+        CapabilityRequest {
+            object_type: ObjectType::Endpoint,
+            permissions: Permissions::full(),
+            reason: "Receive clipboard requests and send responses",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Storage,
+            permissions: Permissions::read_write(),
+            reason: "Persist this machine's clipboard sync settings",
+            required: true,
+        },
+    ],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Print Service manifest
+pub static PRINT_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.print",
+    name: "Print Service",
+    version: "1.0.0",
+    description: "Printing and PDF export for Zero OS",
+    capabilities: &[
+        CapabilityRequest {
+            object_type: ObjectType::Endpoint,
+            permissions: Permissions::full(),
+            reason: "Receive print jobs and send responses",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Storage,
+            permissions: Permissions::read_write(),
+            reason: "Write exported PDFs to the user's Documents",
+            required: true,
+        },
+    ],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Thumbnail Service manifest
+pub static THUMBNAIL_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.thumbnail",
+    name: "Thumbnail Service",
+    version: "1.0.0",
+    description: "Image thumbnails for Zero OS",
+    capabilities: &[
+        CapabilityRequest {
+            object_type: ObjectType::Endpoint,
+            permissions: Permissions::full(),
+            reason: "Receive thumbnail requests and send responses",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Storage,
+            permissions: Permissions::read_write(),
+            reason: "Read images and cache their thumbnails",
+            required: true,
+        },
+    ],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Archive Service manifest
+pub static ARCHIVE_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.archive",
+    name: "Archive Service",
+    version: "1.0.0",
+    description: "Zip and tar archives for Zero OS",
+    capabilities: &[
+        CapabilityRequest {
+            object_type: ObjectType::Endpoint,
+            permissions: Permissions::full(),
+            reason: "Receive archive requests and send responses",
+            required: true,
+        },
+        CapabilityRequest {
+            object_type: ObjectType::Storage,
+            permissions: Permissions::read_write(),
+            reason: "Read and write archives and their entries",
+            required: true,
+        },
+    ],
+    commands: &[],
+    memory_limit: None,
+};
+
+/// Command Service manifest
+pub static COMMANDS_MANIFEST: AppManifest = AppManifest {
+    id: "com.zero.commands",
+    name: "Command Service",
+    version: "1.0.0",
+    description: "App commands for the Zero OS command palette",
+    capabilities: &[CapabilityRequest {
+        object_type: ObjectType::Endpoint,
+        permissions: Permissions::full(),
+        reason: "Receive command registrations and palette requests and send responses",
+        required: true,
+    }],
+    commands: &[],
+    memory_limit: None,
 };
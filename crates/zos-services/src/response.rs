@@ -0,0 +1,66 @@
+//! JSON responses from services
+//!
+//! Services answer a request with a JSON body, sent on the reply capability
+//! the client transferred with the request. Without one, or if that send
+//! fails, the body goes out on the debug channel as
+//! `SERVICE:RESPONSE:{pid}:{tag}:{hex}` for the supervisor to route.
+//!
+//! A service gets `send_response` and `send_error_response` by implementing
+//! [`JsonResponder`] with the name it logs under.
+
+use alloc::format;
+use alloc::string::String;
+use zos_apps::syscall;
+use zos_apps::AppError;
+
+/// Sending JSON responses to clients.
+pub trait JsonResponder {
+    /// Name used in log messages, e.g. "UpdateService"
+    const SERVICE_NAME: &'static str;
+
+    /// Send a JSON response via reply cap, falling back to the debug channel.
+    fn send_response(
+        &self,
+        to_pid: u32,
+        cap_slots: &[u32],
+        tag: u32,
+        json: &[u8],
+    ) -> Result<(), AppError> {
+        send_json(Self::SERVICE_NAME, to_pid, cap_slots, tag, json)
+    }
+
+    /// Send `{"error": error}`.
+    fn send_error_response(
+        &self,
+        to_pid: u32,
+        cap_slots: &[u32],
+        tag: u32,
+        error: &str,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_vec(&serde_json::json!({ "error": error })).unwrap_or_default();
+        self.send_response(to_pid, cap_slots, tag, &json)
+    }
+}
+
+/// Send `json` to `to_pid` via reply cap, falling back to the debug channel.
+pub fn send_json(
+    service: &str,
+    to_pid: u32,
+    cap_slots: &[u32],
+    tag: u32,
+    json: &[u8],
+) -> Result<(), AppError> {
+    if let Some(&reply_slot) = cap_slots.first() {
+        match syscall::send(reply_slot, tag, json) {
+            Ok(()) => return Ok(()),
+            Err(e) => syscall::debug(&format!(
+                "{}: Reply cap send failed ({}), falling back to debug channel",
+                service, e
+            )),
+        }
+    }
+
+    let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+    syscall::debug(&format!("SERVICE:RESPONSE:{}:{:08x}:{}", to_pid, tag, hex));
+    Ok(())
+}
@@ -0,0 +1,230 @@
+//! Creating archives
+//!
+//! The source is walked first, so the job knows how many bytes it will
+//! write and nothing is written for a source that is too big. Entries are
+//! then streamed one at a time from their own read handle into the
+//! archive's write handle. Symlinks are not followed or archived.
+
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use zos_vfs::client::futures::Vfs;
+
+use super::io::{Reader, Sink, Tracker, Writer};
+use super::jobs::Format;
+use super::{tar, zip, MAX_ENTRIES, MAX_TOTAL_BYTES};
+
+/// A file or directory to archive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Entry {
+    /// Path in the VFS
+    path: String,
+    /// Name in the archive, relative and without a trailing `/`
+    name: String,
+    is_dir: bool,
+    size: u64,
+    modified_at: u64,
+}
+
+/// A zip entry written so far, for the central directory.
+struct Written {
+    name: String,
+    datetime: (u16, u16),
+    crc32: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Archive `source` (a file or a directory) into a new file `archive`.
+/// A partly written archive is deleted.
+pub async fn create(
+    vfs: Vfs,
+    tracker: Rc<Tracker>,
+    source: String,
+    archive: String,
+    format: Format,
+) -> Result<(), String> {
+    if vfs.exists(&archive).await? {
+        return Err(format!("{} already exists", archive));
+    }
+    let entries = walk(&vfs, &source).await?;
+    tracker.set_total(entries.iter().map(|e| e.size).sum());
+
+    let handle = vfs.open(&archive, true, true).await?;
+    let mut writer = Writer::new(vfs.clone(), handle);
+    let result = match format {
+        Format::Zip => write_zip(&vfs, &tracker, &entries, &mut writer).await,
+        Format::Tar => write_tar(&vfs, &tracker, &entries, &mut writer).await,
+    };
+    let result = match result {
+        Ok(()) => writer.flush().await,
+        Err(e) => Err(e),
+    };
+    let closed = vfs.close(handle).await;
+    if result.is_err() {
+        let _ = vfs.unlink(&archive).await;
+    }
+    result.and(closed)
+}
+
+/// List `source` and everything under it, parents first.
+async fn walk(vfs: &Vfs, source: &str) -> Result<Vec<Entry>, String> {
+    let inode = vfs.stat(source).await?;
+    let root = String::from(source.rsplit('/').next().unwrap_or_default());
+    if root.is_empty() {
+        return Err(String::from("Cannot archive the root directory"));
+    }
+    if inode.is_symlink() {
+        return Err(format!("{} is a symlink", source));
+    }
+    let mut entries = alloc::vec![Entry {
+        path: String::from(source),
+        name: root,
+        is_dir: inode.is_directory(),
+        size: if inode.is_file() { inode.size } else { 0 },
+        modified_at: inode.modified_at,
+    }];
+    let mut total = entries[0].size;
+    let mut next = 0;
+    while next < entries.len() {
+        if entries[next].is_dir {
+            let (path, name) = (entries[next].path.clone(), entries[next].name.clone());
+            for child in vfs.readdir(&path).await? {
+                if child.is_symlink {
+                    continue;
+                }
+                if entries.len() >= MAX_ENTRIES {
+                    return Err(format!("Source has more than {} entries", MAX_ENTRIES));
+                }
+                total += child.size;
+                if total > MAX_TOTAL_BYTES {
+                    return Err(format!("Source exceeds {} bytes", MAX_TOTAL_BYTES));
+                }
+                entries.push(Entry {
+                    path: format!("{}/{}", path, child.name),
+                    name: format!("{}/{}", name, child.name),
+                    is_dir: child.is_directory,
+                    size: if child.is_directory { 0 } else { child.size },
+                    modified_at: child.modified_at,
+                });
+            }
+        }
+        next += 1;
+    }
+    Ok(entries)
+}
+
+/// Stream a file's content into `sink`. The file must still have the size
+/// it was listed with.
+async fn copy_file(vfs: &Vfs, entry: &Entry, sink: &mut Sink<'_>) -> Result<(), String> {
+    let handle = vfs.open(&entry.path, false, false).await?;
+    let mut reader = Reader::new(vfs.clone(), handle, 0, entry.size);
+    let copied = reader.copy_to(entry.size, sink).await;
+    let closed = vfs.close(handle).await;
+    copied
+        .map_err(|e| format!("{}: {} (was it changed while archiving?)", entry.path, e))
+        .and(closed)
+}
+
+async fn write_zip(
+    vfs: &Vfs,
+    tracker: &Rc<Tracker>,
+    entries: &[Entry],
+    writer: &mut Writer,
+) -> Result<(), String> {
+    let mut written = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let name = if entry.is_dir {
+            format!("{}/", entry.name)
+        } else {
+            entry.name.clone()
+        };
+        let datetime = zip::dos_datetime(entry.modified_at);
+        let offset = writer.position();
+        writer.write(&zip::local_header(&name, datetime)).await?;
+        let mut sink = Sink {
+            writer,
+            tracker,
+            crc: zip::Crc32::default(),
+            written: 0,
+            limit: entry.size,
+        };
+        if !entry.is_dir {
+            copy_file(vfs, entry, &mut sink).await?;
+        }
+        let crc32 = sink.crc.finish();
+        let size = entry.size as u32;
+        writer
+            .patch(
+                offset + zip::LOCAL_CRC_OFFSET,
+                &zip::local_sizes(crc32, size),
+            )
+            .await?;
+        tracker.add_entry();
+        written.push(Written {
+            name,
+            datetime,
+            crc32,
+            size,
+            offset: offset as u32,
+        });
+    }
+
+    let central_offset = writer.position();
+    for entry in &written {
+        let header = zip::central_header(
+            &entry.name,
+            entry.datetime,
+            entry.crc32,
+            entry.size,
+            entry.offset,
+        );
+        writer.write(&header).await?;
+    }
+    let central_size = writer.position() - central_offset;
+    writer
+        .write(&zip::end_of_central_directory(
+            written.len() as u16,
+            central_size as u32,
+            central_offset as u32,
+        ))
+        .await
+}
+
+async fn write_tar(
+    vfs: &Vfs,
+    tracker: &Rc<Tracker>,
+    entries: &[Entry],
+    writer: &mut Writer,
+) -> Result<(), String> {
+    for entry in entries {
+        let kind = if entry.is_dir {
+            tar::EntryKind::Directory
+        } else {
+            tar::EntryKind::File
+        };
+        writer
+            .write(&tar::entry_headers(
+                &entry.name,
+                kind,
+                entry.size,
+                entry.modified_at / 1000,
+            ))
+            .await?;
+        if !entry.is_dir {
+            let mut sink = Sink {
+                writer,
+                tracker,
+                crc: zip::Crc32::default(),
+                written: 0,
+                limit: entry.size,
+            };
+            copy_file(vfs, entry, &mut sink).await?;
+            let padding = [0u8; tar::BLOCK];
+            writer.write(&padding[..tar::padding(entry.size)]).await?;
+        }
+        tracker.add_entry();
+    }
+    writer.write(&tar::end_of_archive()).await
+}
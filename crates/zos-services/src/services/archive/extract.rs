@@ -0,0 +1,400 @@
+//! Extracting archives
+//!
+//! The format is sniffed from the first bytes. Entries are written under a
+//! new destination directory; entry names are checked so that nothing can
+//! land outside it (see [`entry_path`]), and the counts and sizes an
+//! archive declares are capped and then enforced while writing, so a small
+//! archive can't expand without bound: each entry to at most
+//! [`MAX_ENTRY_BYTES`], the whole archive to at most [`MAX_TOTAL_BYTES`].
+//!
+//! Zip entries are located through the central directory, and checked
+//! against their CRC-32. Tar archives are read front to back.
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use zos_vfs::client::futures::Vfs;
+
+use super::inflate::inflate;
+use super::io::{Reader, Sink, Tracker, Writer};
+use super::jobs::Format;
+use super::{tar, zip, MAX_ENTRIES, MAX_ENTRY_BYTES, MAX_TOTAL_BYTES};
+
+/// Largest zip central directory that will be read, in bytes.
+const MAX_CENTRAL_DIRECTORY: u64 = 1024 * 1024;
+
+/// Largest pax header or GNU long name that will be read, in bytes.
+const MAX_TAR_METADATA: u64 = 64 * 1024;
+
+/// The relative path an entry name extracts to, or `None` for a name with
+/// nothing in it (like `./`). Backslashes are taken as separators; absolute
+/// names and `..` components are refused.
+pub fn entry_path(name: &str) -> Result<Option<String>, String> {
+    let name = name.replace('\\', "/");
+    if name.starts_with('/') || name.split('/').next().is_some_and(|c| c.contains(':')) {
+        return Err(format!("Entry {} has an absolute path", name));
+    }
+    let mut parts = Vec::new();
+    for part in name.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => return Err(format!("Entry {} points outside the archive", name)),
+            part => parts.push(part),
+        }
+    }
+    if parts.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(parts.join("/")))
+}
+
+/// Content bytes extracted so far, held to the size limits.
+#[derive(Default)]
+struct Budget {
+    bytes: u64,
+}
+
+impl Budget {
+    /// Count an entry of `size` bytes, refusing it past either limit.
+    fn take(&mut self, path: &str, size: u64) -> Result<(), String> {
+        if size > MAX_ENTRY_BYTES {
+            return Err(format!(
+                "Entry {} expands to more than {} bytes",
+                path, MAX_ENTRY_BYTES
+            ));
+        }
+        self.bytes += size;
+        if self.bytes > MAX_TOTAL_BYTES {
+            return Err(format!(
+                "Archive expands to more than {} bytes",
+                MAX_TOTAL_BYTES
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Where the entries go, and what has been made there.
+struct Destination {
+    vfs: Vfs,
+    root: String,
+    dirs: BTreeSet<String>,
+    files: BTreeSet<String>,
+    budget: Budget,
+}
+
+impl Destination {
+    /// Make a directory entry, and its parents.
+    async fn dir(&mut self, path: &str) -> Result<(), String> {
+        let mut end = 0;
+        while end < path.len() {
+            end = path[end + 1..]
+                .find('/')
+                .map(|i| end + 1 + i)
+                .unwrap_or(path.len());
+            let dir = &path[..end];
+            if self.files.contains(dir) {
+                return Err(format!("Entry {} is both a file and a directory", dir));
+            }
+            if self.dirs.insert(String::from(dir)) {
+                self.vfs.mkdir(&format!("{}/{}", self.root, dir)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Open a new file for an entry, making its parents.
+    async fn file(&mut self, path: &str) -> Result<u32, String> {
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            self.dir(parent).await?;
+        }
+        if self.dirs.contains(path) || !self.files.insert(String::from(path)) {
+            return Err(format!("Entry {} appears twice", path));
+        }
+        self.vfs
+            .open(&format!("{}/{}", self.root, path), true, true)
+            .await
+    }
+
+    fn check_count(&self) -> Result<(), String> {
+        if self.dirs.len() + self.files.len() >= MAX_ENTRIES {
+            return Err(format!("Archive has more than {} entries", MAX_ENTRIES));
+        }
+        Ok(())
+    }
+}
+
+/// Extract `archive` into a new directory `dest`. Entries extracted before
+/// a failure are left in place.
+pub async fn extract(
+    vfs: Vfs,
+    tracker: Rc<Tracker>,
+    archive: String,
+    dest: String,
+) -> Result<(), String> {
+    let size = vfs.stat(&archive).await?.size;
+    if vfs.exists(&dest).await? {
+        return Err(format!("{} already exists", dest));
+    }
+    let handle = vfs.open(&archive, false, false).await?;
+    let result = extract_from(&vfs, &tracker, handle, size, dest).await;
+    let closed = vfs.close(handle).await;
+    result.and(closed)
+}
+
+async fn extract_from(
+    vfs: &Vfs,
+    tracker: &Rc<Tracker>,
+    handle: u32,
+    size: u64,
+    dest: String,
+) -> Result<(), String> {
+    let head = Reader::new(vfs.clone(), handle, 0, size.min(tar::BLOCK as u64))
+        .read(size.min(tar::BLOCK as u64) as usize)
+        .await?;
+    let format = if zip::is_zip(&head) {
+        Format::Zip
+    } else if head.len() == tar::BLOCK
+        && matches!(
+            tar::parse_header(head.as_slice().try_into().unwrap()),
+            Ok(Some(_))
+        )
+    {
+        Format::Tar
+    } else {
+        return Err(String::from("Not a zip or tar archive"));
+    };
+    tracker.format.set(Some(format));
+
+    vfs.mkdir(&dest).await?;
+    let mut dest = Destination {
+        vfs: vfs.clone(),
+        root: dest,
+        dirs: BTreeSet::new(),
+        files: BTreeSet::new(),
+        budget: Budget::default(),
+    };
+    match format {
+        Format::Zip => extract_zip(vfs, tracker, handle, size, &mut dest).await,
+        Format::Tar => extract_tar(vfs, tracker, handle, size, &mut dest).await,
+    }
+}
+
+async fn extract_zip(
+    vfs: &Vfs,
+    tracker: &Rc<Tracker>,
+    handle: u32,
+    size: u64,
+    dest: &mut Destination,
+) -> Result<(), String> {
+    let tail_len = size.min(zip::MAX_EOCD_SEARCH as u64);
+    let tail = Reader::new(vfs.clone(), handle, size - tail_len, size)
+        .read(tail_len as usize)
+        .await?;
+    let directory = zip::find_end_of_central_directory(&tail)?;
+    if directory.entries as usize > MAX_ENTRIES {
+        return Err(format!("Archive has more than {} entries", MAX_ENTRIES));
+    }
+    if directory.size > MAX_CENTRAL_DIRECTORY || directory.offset + directory.size > size {
+        return Err(String::from("Corrupt zip central directory"));
+    }
+    let central = Reader::new(
+        vfs.clone(),
+        handle,
+        directory.offset,
+        directory.offset + directory.size,
+    )
+    .read(directory.size as usize)
+    .await?;
+    let entries = zip::parse_central_directory(&central, directory.entries)?;
+    drop(central);
+
+    let total: u64 = entries.iter().map(|e| e.size).sum();
+    if total > MAX_TOTAL_BYTES {
+        return Err(format!(
+            "Archive expands to more than {} bytes",
+            MAX_TOTAL_BYTES
+        ));
+    }
+    tracker.set_total(total);
+
+    for entry in &entries {
+        zip::check_method(entry)?;
+        dest.check_count()?;
+        let Some(path) = entry_path(&entry.name)? else {
+            continue;
+        };
+        if entry.is_dir() {
+            dest.dir(&path).await?;
+            tracker.add_entry();
+            continue;
+        }
+
+        let header = Reader::new(
+            vfs.clone(),
+            handle,
+            entry.local_offset,
+            size.min(entry.local_offset + zip::LOCAL_HEADER_LEN as u64),
+        )
+        .read(zip::LOCAL_HEADER_LEN)
+        .await?;
+        let start = entry.local_offset + zip::local_data_offset(&header)?;
+        let end = start + entry.compressed_size;
+        if end > size {
+            return Err(format!("Entry {} runs past the end of the archive", path));
+        }
+        let mut reader = Reader::new(vfs.clone(), handle, start, end);
+
+        // Counted at its declared size, which the sink then holds it to
+        dest.budget.take(&path, entry.size)?;
+        let file = dest.file(&path).await?;
+        let mut writer = Writer::new(vfs.clone(), file);
+        let mut sink = Sink {
+            writer: &mut writer,
+            tracker,
+            crc: zip::Crc32::default(),
+            written: 0,
+            limit: entry.size,
+        };
+        let result = match entry.method {
+            zip::METHOD_STORED => reader.copy_to(entry.compressed_size, &mut sink).await,
+            _ => inflate(&mut reader, &mut sink, MAX_ENTRY_BYTES)
+                .await
+                .map(|_| ()),
+        };
+        let (crc32, written) = (sink.crc.finish(), sink.written);
+        let result = match result {
+            Ok(()) => writer.flush().await,
+            Err(e) => Err(e),
+        };
+        let closed = vfs.close(file).await;
+        result.map_err(|e| format!("{}: {}", path, e))?;
+        closed?;
+        if written != entry.size || crc32 != entry.crc32 {
+            return Err(format!("{}: checksum mismatch", path));
+        }
+        tracker.add_entry();
+    }
+    Ok(())
+}
+
+async fn extract_tar(
+    vfs: &Vfs,
+    tracker: &Rc<Tracker>,
+    handle: u32,
+    size: u64,
+    dest: &mut Destination,
+) -> Result<(), String> {
+    // Content can't exceed the archive, so the archive size is the total
+    tracker.set_total(size);
+    let mut reader = Reader::new(vfs.clone(), handle, 0, size);
+    let mut long_name: Option<String> = None;
+    loop {
+        if reader.remaining() < tar::BLOCK as u64 {
+            // Tolerate archives missing their end blocks
+            return Ok(());
+        }
+        let block = reader.read(tar::BLOCK).await?;
+        let Some(header) = tar::parse_header(block.as_slice().try_into().unwrap())? else {
+            return Ok(());
+        };
+        let padded = header.size + tar::padding(header.size) as u64;
+        if padded > reader.remaining() {
+            return Err(format!(
+                "Entry {} runs past the end of the archive",
+                header.path
+            ));
+        }
+
+        match header.kind {
+            tar::EntryKind::PaxHeader | tar::EntryKind::LongName => {
+                if header.size > MAX_TAR_METADATA {
+                    return Err(String::from("Tar extended header is too large"));
+                }
+                let data = reader.read(header.size as usize).await?;
+                reader.skip(tar::padding(header.size) as u64).await?;
+                long_name = if header.kind == tar::EntryKind::PaxHeader {
+                    tar::pax_path(&data).or(long_name)
+                } else {
+                    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+                    Some(String::from_utf8_lossy(&data[..end]).into_owned())
+                };
+                continue;
+            }
+            tar::EntryKind::PaxGlobal | tar::EntryKind::Other => {
+                reader.skip(padded).await?;
+                long_name = None;
+                continue;
+            }
+            tar::EntryKind::File | tar::EntryKind::Directory => {}
+        }
+
+        let name = long_name.take().unwrap_or(header.path);
+        dest.check_count()?;
+        let Some(path) = entry_path(&name)? else {
+            reader.skip(padded).await?;
+            continue;
+        };
+        if header.kind == tar::EntryKind::Directory {
+            dest.dir(&path).await?;
+            reader.skip(padded).await?;
+            tracker.add_entry();
+            continue;
+        }
+
+        dest.budget.take(&path, header.size)?;
+        let file = dest.file(&path).await?;
+        let mut writer = Writer::new(vfs.clone(), file);
+        let mut sink = Sink {
+            writer: &mut writer,
+            tracker,
+            crc: zip::Crc32::default(),
+            written: 0,
+            limit: header.size,
+        };
+        let result = match reader.copy_to(header.size, &mut sink).await {
+            Ok(()) => writer.flush().await,
+            Err(e) => Err(e),
+        };
+        let closed = vfs.close(file).await;
+        result.map_err(|e| format!("{}: {}", path, e))?;
+        closed?;
+        reader.skip(tar::padding(header.size) as u64).await?;
+        tracker.add_entry();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_paths() {
+        assert_eq!(
+            entry_path("docs/./readme.txt").unwrap(),
+            Some(String::from("docs/readme.txt"))
+        );
+        assert_eq!(
+            entry_path("docs\\sub\\").unwrap(),
+            Some(String::from("docs/sub"))
+        );
+        assert_eq!(entry_path("./").unwrap(), None);
+        assert!(entry_path("/etc/passwd").is_err());
+        assert!(entry_path("C:/Windows").is_err());
+        assert!(entry_path("docs/../../x").is_err());
+        assert!(entry_path("\\\\server\\share").is_err());
+    }
+
+    #[test]
+    fn test_budget() {
+        let mut budget = Budget::default();
+        assert!(budget.take("a", MAX_ENTRY_BYTES).is_ok());
+        assert!(budget.take("b", MAX_ENTRY_BYTES + 1).is_err());
+        for _ in 1..MAX_TOTAL_BYTES / MAX_ENTRY_BYTES {
+            assert!(budget.take("c", MAX_ENTRY_BYTES).is_ok());
+        }
+        assert!(budget.take("d", 1).is_err());
+    }
+}
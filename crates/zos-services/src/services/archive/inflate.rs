@@ -0,0 +1,422 @@
+//! Streaming DEFLATE decoder (RFC 1951)
+//!
+//! Zip entries are almost always deflated. This decoder pulls compressed
+//! bytes from an [`Input`] and pushes decompressed bytes to an [`Output`]
+//! as it goes, so memory use is the 32 KiB back-reference window and the
+//! Huffman tables, whatever the size of the entry. Both ends are async so
+//! they can be VFS file handles.
+//!
+//! Huffman codes are decoded a bit at a time from canonical code counts,
+//! as in zlib's `puff`: slower than table lookup, but small and obviously
+//! correct, and the VFS round trips dominate anyway.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Source of compressed bytes.
+pub(crate) trait Input {
+    /// Next byte; an error at the end of the input.
+    async fn byte(&mut self) -> Result<u8, String>;
+}
+
+/// Sink for decompressed bytes.
+pub(crate) trait Output {
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), String>;
+}
+
+/// Back-reference window size.
+const WINDOW: usize = 32 * 1024;
+
+/// Decompressed bytes collected before they are passed to the output.
+const FLUSH_AT: usize = 2048;
+
+const MAX_BITS: usize = 15;
+
+/// Base lengths of length codes 257..285, and their extra bits.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Base distances of distance codes 0..29, and their extra bits.
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Order in which code length code lengths are stored.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// A canonical Huffman code: how many codes of each length, and the
+/// symbols in code order.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Build a code from per-symbol code lengths (0 = unused). Incomplete
+    /// codes are allowed, as RFC 1951 permits for single-code distance
+    /// trees; over-subscribed ones are not.
+    fn new(lengths: &[u8]) -> Result<Self, String> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(String::from("Invalid Huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0u16; offsets[MAX_BITS + 1] as usize];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+}
+
+/// Reads bits least significant first, and keeps the window.
+struct Inflater<'a, I, O> {
+    input: &'a mut I,
+    output: &'a mut O,
+    bit_buf: u32,
+    bit_count: u32,
+    window: Vec<u8>,
+    /// Bytes decompressed so far
+    total: u64,
+    /// Most bytes the stream may decompress to
+    limit: u64,
+    pending: Vec<u8>,
+}
+
+impl<I: Input, O: Output> Inflater<'_, I, O> {
+    async fn bits(&mut self, n: u32) -> Result<u32, String> {
+        while self.bit_count < n {
+            let byte = self.input.byte().await?;
+            self.bit_buf |= (byte as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+        let value = self.bit_buf & ((1u32 << n) - 1);
+        self.bit_buf >>= n;
+        self.bit_count -= n;
+        Ok(value)
+    }
+
+    async fn decode(&mut self, code: &Huffman) -> Result<u16, String> {
+        let mut value: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS {
+            value |= self.bits(1).await? as i32;
+            let count = code.counts[len] as i32;
+            if value - count < first {
+                return Ok(code.symbols[(index + value - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            value <<= 1;
+        }
+        Err(String::from("Invalid Huffman code in data"))
+    }
+
+    async fn emit(&mut self, byte: u8) -> Result<(), String> {
+        if self.total >= self.limit {
+            return Err(format!("Decompresses to more than {} bytes", self.limit));
+        }
+        self.window[(self.total % WINDOW as u64) as usize] = byte;
+        self.total += 1;
+        self.pending.push(byte);
+        if self.pending.len() >= FLUSH_AT {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), String> {
+        if !self.pending.is_empty() {
+            let pending = core::mem::take(&mut self.pending);
+            self.output.write(&pending).await?;
+            self.pending = pending;
+            self.pending.clear();
+        }
+        Ok(())
+    }
+
+    async fn stored(&mut self) -> Result<(), String> {
+        // Stored blocks start on a byte boundary
+        self.bit_buf = 0;
+        self.bit_count = 0;
+        let len = self.bits(16).await?;
+        let nlen = self.bits(16).await?;
+        if len != !nlen & 0xffff {
+            return Err(String::from("Stored block length check failed"));
+        }
+        for _ in 0..len {
+            let byte = self.input.byte().await?;
+            self.emit(byte).await?;
+        }
+        Ok(())
+    }
+
+    async fn codes(&mut self, literals: &Huffman, distances: &Huffman) -> Result<(), String> {
+        loop {
+            let symbol = self.decode(literals).await?;
+            match symbol {
+                0..=255 => self.emit(symbol as u8).await?,
+                256 => return Ok(()),
+                257..=285 => {
+                    let i = (symbol - 257) as usize;
+                    let len = LENGTH_BASE[i] as u32 + self.bits(LENGTH_EXTRA[i] as u32).await?;
+                    let d = self.decode(distances).await? as usize;
+                    if d >= DIST_BASE.len() {
+                        return Err(format!("Invalid distance code {}", d));
+                    }
+                    let dist = DIST_BASE[d] as u64 + self.bits(DIST_EXTRA[d] as u32).await? as u64;
+                    if dist > self.total {
+                        return Err(String::from("Distance too far back"));
+                    }
+                    for _ in 0..len {
+                        let at = ((self.total - dist) % WINDOW as u64) as usize;
+                        let byte = self.window[at];
+                        self.emit(byte).await?;
+                    }
+                }
+                _ => return Err(format!("Invalid literal/length code {}", symbol)),
+            }
+        }
+    }
+
+    fn fixed() -> Result<(Huffman, Huffman), String> {
+        let mut lengths = [0u8; 288];
+        lengths[..144].fill(8);
+        lengths[144..256].fill(9);
+        lengths[256..280].fill(7);
+        lengths[280..].fill(8);
+        Ok((Huffman::new(&lengths)?, Huffman::new(&[5u8; 30])?))
+    }
+
+    async fn dynamic(&mut self) -> Result<(Huffman, Huffman), String> {
+        let nlen = self.bits(5).await? as usize + 257;
+        let ndist = self.bits(5).await? as usize + 1;
+        let ncode = self.bits(4).await? as usize + 4;
+        if nlen > 286 || ndist > 30 {
+            return Err(String::from("Too many length or distance codes"));
+        }
+
+        let mut code_lengths = [0u8; 19];
+        for &i in &CODE_LENGTH_ORDER[..ncode] {
+            code_lengths[i] = self.bits(3).await? as u8;
+        }
+        let lencode = Huffman::new(&code_lengths)?;
+
+        let mut lengths = vec![0u8; nlen + ndist];
+        let mut i = 0;
+        while i < lengths.len() {
+            let symbol = self.decode(&lencode).await?;
+            let (value, repeat) = match symbol {
+                0..=15 => (symbol as u8, 1),
+                16 => {
+                    if i == 0 {
+                        return Err(String::from("Repeat with no previous length"));
+                    }
+                    (lengths[i - 1], 3 + self.bits(2).await? as usize)
+                }
+                17 => (0, 3 + self.bits(3).await? as usize),
+                _ => (0, 11 + self.bits(7).await? as usize),
+            };
+            if i + repeat > lengths.len() {
+                return Err(String::from("Too many code lengths"));
+            }
+            lengths[i..i + repeat].fill(value);
+            i += repeat;
+        }
+        if lengths[256] == 0 {
+            return Err(String::from("No end-of-block code"));
+        }
+        Ok((
+            Huffman::new(&lengths[..nlen])?,
+            Huffman::new(&lengths[nlen..])?,
+        ))
+    }
+}
+
+/// Decompress one DEFLATE stream from `input` into `output`, failing once
+/// it passes `limit` bytes. Returns the number of bytes written.
+pub(crate) async fn inflate<I: Input, O: Output>(
+    input: &mut I,
+    output: &mut O,
+    limit: u64,
+) -> Result<u64, String> {
+    let mut inflater = Inflater {
+        input,
+        output,
+        bit_buf: 0,
+        bit_count: 0,
+        window: vec![0u8; WINDOW],
+        total: 0,
+        limit,
+        pending: Vec::with_capacity(FLUSH_AT),
+    };
+    loop {
+        let last = inflater.bits(1).await? == 1;
+        match inflater.bits(2).await? {
+            0 => inflater.stored().await?,
+            1 => {
+                let (literals, distances) = Inflater::<I, O>::fixed()?;
+                inflater.codes(&literals, &distances).await?;
+            }
+            2 => {
+                let (literals, distances) = inflater.dynamic().await?;
+                inflater.codes(&literals, &distances).await?;
+            }
+            _ => return Err(String::from("Invalid block type")),
+        }
+        if last {
+            break;
+        }
+    }
+    inflater.flush().await?;
+    Ok(inflater.total)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    /// Run a future whose awaits are all ready at once.
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future is not ready"),
+        }
+    }
+
+    pub(crate) struct SliceInput<'a>(pub &'a [u8]);
+
+    impl Input for SliceInput<'_> {
+        async fn byte(&mut self) -> Result<u8, String> {
+            let (&first, rest) = self
+                .0
+                .split_first()
+                .ok_or_else(|| String::from("Unexpected end of data"))?;
+            self.0 = rest;
+            Ok(first)
+        }
+    }
+
+    impl Output for Vec<u8> {
+        async fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+            self.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    fn inflate_all(data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        let total = block_on(inflate(&mut SliceInput(data), &mut out, u64::MAX))?;
+        assert_eq!(total, out.len() as u64);
+        Ok(out)
+    }
+
+    #[test]
+    fn test_stored_and_fixed_blocks() {
+        // zlib.compressobj(0, wbits=-15): one stored block
+        assert_eq!(
+            inflate_all(&[0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o']).unwrap(),
+            b"hello"
+        );
+        // zlib.compressobj(9, wbits=-15) of "hello hello hello": fixed codes
+        // with a back-reference
+        assert_eq!(
+            inflate_all(&[0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00]).unwrap(),
+            b"hello hello hello"
+        );
+        // Empty input
+        assert_eq!(inflate_all(&[0x03, 0x00]).unwrap(), b"");
+    }
+
+    #[test]
+    fn test_dynamic_block() {
+        // zlib.compressobj(9, wbits=-15) picks dynamic codes for this
+        let compressed = [
+            0x1d, 0x88, 0xc7, 0x11, 0x00, 0x00, 0x0c, 0x82, 0x66, 0xb5, 0xec, 0x3f, 0x43, 0x24,
+            0x3e, 0x90, 0x43, 0x4e, 0xa2, 0x6d, 0xb0, 0xde, 0x8a, 0x2e, 0x9a, 0x1b, 0x4b, 0x34,
+            0x4f, 0xd1, 0x01,
+        ];
+        assert_eq!(
+            inflate_all(&compressed).unwrap(),
+            b"abcccaaaacaabacaaaadcaabccabaabcabadaaaabbadabaaba"
+        );
+    }
+
+    #[test]
+    fn test_back_reference_across_window_wrap() {
+        // A 40000-byte stored block, then a fixed block copying 258 bytes
+        // from the far end of the window
+        let data: Vec<u8> = (0..40000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let mut stream = vec![0x00, 0x40, 0x9c, 0xbf, 0x63];
+        stream.extend_from_slice(&data);
+        stream.extend_from_slice(&[0x1b, 0xbd, 0xff, 0x1f, 0x00]);
+
+        let mut expected = data.clone();
+        expected.extend_from_slice(&data[40000 - 32768..40000 - 32768 + 258]);
+        assert_eq!(inflate_all(&stream).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_corrupt_input() {
+        // Block type 3
+        assert!(inflate_all(&[0x07]).is_err());
+        // Stored length check
+        assert!(inflate_all(&[0x01, 0x05, 0x00, 0x00, 0x00]).is_err());
+        // Truncated
+        assert!(inflate_all(&[0xcb, 0x48, 0xcd]).is_err());
+        // Distance before the start of the output
+        assert!(inflate_all(&[0x03, 0x02, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_limit() {
+        // "hello hello hello" is 17 bytes, mostly a back-reference
+        let stream = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00];
+        let mut out = Vec::new();
+        assert_eq!(
+            block_on(inflate(&mut SliceInput(&stream), &mut out, 17)),
+            Ok(17)
+        );
+        let mut out = Vec::new();
+        assert!(block_on(inflate(&mut SliceInput(&stream), &mut out, 16)).is_err());
+        assert!(out.len() <= 16);
+    }
+}
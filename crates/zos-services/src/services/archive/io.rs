@@ -0,0 +1,220 @@
+//! Streaming through VFS file handles
+//!
+//! Archives and their entries are never held whole: a [`Reader`] pulls a
+//! range of a file through read-at a chunk at a time, and a [`Writer`]
+//! appends through write-at, so the service holds a few KiB per job
+//! whatever the size of the archive.
+
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use zos_vfs::client::futures::Vfs;
+
+use super::inflate::{Input, Output};
+use super::jobs::{Format, Progress};
+use super::zip::Crc32;
+use crate::services::vfs::handlers::handles::MAX_READ_AT_LEN;
+
+/// Bytes per read-at or write-at.
+pub const CHUNK: usize = MAX_READ_AT_LEN as usize;
+
+/// A job's progress, shared between its task and the service so the
+/// service can publish it between VFS calls.
+#[derive(Debug, Default)]
+pub struct Tracker {
+    pub progress: Cell<Progress>,
+    pub format: Cell<Option<Format>>,
+}
+
+impl Tracker {
+    pub fn add_entry(&self) {
+        let mut progress = self.progress.get();
+        progress.entries += 1;
+        self.progress.set(progress);
+    }
+
+    pub fn add_bytes(&self, bytes: u64) {
+        let mut progress = self.progress.get();
+        progress.bytes += bytes;
+        self.progress.set(progress);
+    }
+
+    pub fn set_total(&self, total_bytes: u64) {
+        let mut progress = self.progress.get();
+        progress.total_bytes = Some(total_bytes);
+        self.progress.set(progress);
+    }
+}
+
+/// Reads `offset..end` of an open file.
+pub struct Reader {
+    vfs: Vfs,
+    handle: u32,
+    offset: u64,
+    end: u64,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl Reader {
+    pub fn new(vfs: Vfs, handle: u32, offset: u64, end: u64) -> Self {
+        Self {
+            vfs,
+            handle,
+            offset,
+            end,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Bytes left in the range.
+    pub fn remaining(&self) -> u64 {
+        self.end - self.offset + (self.buf.len() - self.pos) as u64
+    }
+
+    /// The next `len` bytes.
+    pub async fn read(&mut self, len: usize) -> Result<Vec<u8>, String> {
+        let mut out = Vec::with_capacity(len);
+        while out.len() < len {
+            let chunk = self.next_chunk(len - out.len()).await?;
+            out.extend_from_slice(chunk);
+        }
+        Ok(out)
+    }
+
+    /// Skip the next `len` bytes.
+    pub async fn skip(&mut self, len: u64) -> Result<(), String> {
+        let buffered = (self.buf.len() - self.pos) as u64;
+        if len <= buffered {
+            self.pos += len as usize;
+            return Ok(());
+        }
+        let rest = len - buffered;
+        if rest > self.end - self.offset {
+            return Err(String::from("Unexpected end of archive"));
+        }
+        self.buf.clear();
+        self.pos = 0;
+        self.offset += rest;
+        Ok(())
+    }
+
+    /// Pass the next `len` bytes to `output`.
+    pub async fn copy_to<O: Output>(&mut self, len: u64, output: &mut O) -> Result<(), String> {
+        let mut left = len;
+        while left > 0 {
+            let chunk = self.next_chunk(left.min(CHUNK as u64) as usize).await?;
+            left -= chunk.len() as u64;
+            output.write(chunk).await?;
+        }
+        Ok(())
+    }
+
+    /// Up to `max` buffered bytes, reading a chunk first if none are.
+    async fn next_chunk(&mut self, max: usize) -> Result<&[u8], String> {
+        if self.pos == self.buf.len() {
+            let len = (self.end - self.offset).min(CHUNK as u64);
+            self.buf = if len == 0 {
+                Vec::new()
+            } else {
+                self.vfs.read_at(self.handle, self.offset, len).await?
+            };
+            self.pos = 0;
+            if self.buf.is_empty() {
+                return Err(String::from("Unexpected end of archive"));
+            }
+            self.buf.truncate(len as usize);
+            self.offset += self.buf.len() as u64;
+        }
+        let start = self.pos;
+        self.pos = (start + max).min(self.buf.len());
+        Ok(&self.buf[start..self.pos])
+    }
+}
+
+impl Input for Reader {
+    async fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.next_chunk(1).await?[0])
+    }
+}
+
+/// Appends to an open file from `offset`.
+pub struct Writer {
+    vfs: Vfs,
+    handle: u32,
+    offset: u64,
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new(vfs: Vfs, handle: u32) -> Self {
+        Self {
+            vfs,
+            handle,
+            offset: 0,
+            buf: Vec::with_capacity(CHUNK),
+        }
+    }
+
+    /// Bytes written so far, including buffered ones.
+    pub fn position(&self) -> u64 {
+        self.offset + self.buf.len() as u64
+    }
+
+    pub async fn write(&mut self, mut bytes: &[u8]) -> Result<(), String> {
+        while !bytes.is_empty() {
+            let take = (CHUNK - self.buf.len()).min(bytes.len());
+            self.buf.extend_from_slice(&bytes[..take]);
+            bytes = &bytes[take..];
+            if self.buf.len() == CHUNK {
+                self.flush().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Overwrite bytes already written at `offset`.
+    pub async fn patch(&mut self, offset: u64, bytes: &[u8]) -> Result<(), String> {
+        self.flush().await?;
+        self.vfs.write_at(self.handle, offset, bytes).await?;
+        Ok(())
+    }
+
+    pub async fn flush(&mut self) -> Result<(), String> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.vfs
+            .write_at(self.handle, self.offset, &self.buf)
+            .await?;
+        self.offset += self.buf.len() as u64;
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+/// Content of one entry on its way into a [`Writer`]: checksummed,
+/// counted against a limit and reported to the job's [`Tracker`].
+pub struct Sink<'a> {
+    pub writer: &'a mut Writer,
+    pub tracker: &'a Rc<Tracker>,
+    pub crc: Crc32,
+    pub written: u64,
+    /// Most bytes the entry may have
+    pub limit: u64,
+}
+
+impl Output for Sink<'_> {
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.written += bytes.len() as u64;
+        if self.written > self.limit {
+            return Err(format!("Entry exceeds its size of {} bytes", self.limit));
+        }
+        self.crc.update(bytes);
+        self.tracker.add_bytes(bytes.len() as u64);
+        self.writer.write(bytes).await
+    }
+}
@@ -0,0 +1,285 @@
+//! Archive job table
+//!
+//! A job is `running` from the request until its archive is written or
+//! extracted, and ends `completed` or `failed`. Progress is counted in
+//! entries and content bytes; `total_bytes` is known up front when
+//! extracting a zip, and once the source has been walked when creating.
+//!
+//! Finished jobs are kept for status queries until [`MAX_FINISHED_JOBS`]
+//! newer ones have finished.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Maximum running jobs (DoS protection per Rule 11).
+pub const MAX_ACTIVE_JOBS: usize = 4;
+
+/// Maximum running jobs of one process.
+pub const MAX_JOBS_PER_PID: usize = 2;
+
+/// Finished jobs kept for status queries.
+pub const MAX_FINISHED_JOBS: usize = 32;
+
+/// Archive format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Zip,
+    Tar,
+}
+
+/// What a job does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobKind {
+    Create,
+    Extract,
+}
+
+/// Where a job is up to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Entries and bytes done so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Progress {
+    pub entries: u32,
+    pub bytes: u64,
+    /// Content bytes in all, once known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+}
+
+/// An archive job as reported to apps and on the event bus.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ArchiveJob {
+    pub id: u32,
+    /// Requesting process
+    pub pid: u32,
+    pub kind: JobKind,
+    /// Format written, or detected when extracting (once known)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<Format>,
+    pub archive: String,
+    /// Source of a create, destination of an extract
+    pub path: String,
+    pub status: JobStatus,
+    #[serde(flatten)]
+    pub progress: Progress,
+    /// Wall-clock time of the request (ms)
+    pub started_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// All jobs.
+#[derive(Debug)]
+pub struct JobTable {
+    jobs: BTreeMap<u32, ArchiveJob>,
+    next_id: u32,
+}
+
+impl Default for JobTable {
+    fn default() -> Self {
+        Self {
+            jobs: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl JobTable {
+    /// Start a job.
+    pub fn start(
+        &mut self,
+        pid: u32,
+        kind: JobKind,
+        format: Option<Format>,
+        archive: String,
+        path: String,
+        now_ms: u64,
+    ) -> Result<ArchiveJob, String> {
+        let running: Vec<&ArchiveJob> = self
+            .jobs
+            .values()
+            .filter(|job| job.status == JobStatus::Running)
+            .collect();
+        if running.len() >= MAX_ACTIVE_JOBS {
+            return Err(String::from("Too many archive jobs, try again later"));
+        }
+        if running.iter().filter(|job| job.pid == pid).count() >= MAX_JOBS_PER_PID {
+            return Err(format!(
+                "Too many unfinished archive jobs (max {})",
+                MAX_JOBS_PER_PID
+            ));
+        }
+        // Two jobs on one path would trample each other's files
+        if running.iter().any(|job| {
+            [&job.archive, &job.path]
+                .iter()
+                .any(|p| **p == archive || **p == path)
+        }) {
+            return Err(String::from("Path is in use by another archive job"));
+        }
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        let job = ArchiveJob {
+            id,
+            pid,
+            kind,
+            format,
+            archive,
+            path,
+            status: JobStatus::Running,
+            progress: Progress::default(),
+            started_at: now_ms,
+            error: None,
+        };
+        self.jobs.insert(id, job.clone());
+        Ok(job)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&ArchiveJob> {
+        self.jobs.get(&id)
+    }
+
+    /// Jobs of `pid`, oldest first.
+    pub fn jobs_of(&self, pid: u32) -> Vec<ArchiveJob> {
+        self.jobs
+            .values()
+            .filter(|job| job.pid == pid)
+            .cloned()
+            .collect()
+    }
+
+    /// Record a running job's progress. Returns the job if anything
+    /// changed.
+    pub fn update(
+        &mut self,
+        id: u32,
+        progress: Progress,
+        format: Option<Format>,
+    ) -> Option<ArchiveJob> {
+        let job = self
+            .jobs
+            .get_mut(&id)
+            .filter(|job| job.status == JobStatus::Running)?;
+        let format = format.or(job.format);
+        if job.progress == progress && job.format == format {
+            return None;
+        }
+        job.progress = progress;
+        job.format = format;
+        Some(job.clone())
+    }
+
+    /// Record how a running job ended.
+    pub fn finish(&mut self, id: u32, result: Result<(), String>) -> Option<ArchiveJob> {
+        let job = self
+            .jobs
+            .get_mut(&id)
+            .filter(|job| job.status == JobStatus::Running)?;
+        match result {
+            Ok(()) => job.status = JobStatus::Completed,
+            Err(error) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(error);
+            }
+        }
+        let job = job.clone();
+        self.prune();
+        Some(job)
+    }
+
+    /// Forget the oldest finished jobs beyond [`MAX_FINISHED_JOBS`].
+    fn prune(&mut self) {
+        let finished: Vec<u32> = self
+            .jobs
+            .values()
+            .filter(|job| job.status != JobStatus::Running)
+            .map(|job| job.id)
+            .collect();
+        let excess = finished.len().saturating_sub(MAX_FINISHED_JOBS);
+        for id in finished.into_iter().take(excess) {
+            self.jobs.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start(table: &mut JobTable, pid: u32, archive: &str) -> Result<ArchiveJob, String> {
+        table.start(
+            pid,
+            JobKind::Create,
+            Some(Format::Zip),
+            String::from(archive),
+            format!("{}.d", archive),
+            5,
+        )
+    }
+
+    #[test]
+    fn test_progress_and_finish() {
+        let mut table = JobTable::default();
+        let id = start(&mut table, 10, "/home/1/a.zip").unwrap().id;
+        let progress = Progress {
+            entries: 2,
+            bytes: 10,
+            total_bytes: Some(20),
+        };
+        assert_eq!(table.update(id, progress, None).unwrap().progress, progress);
+        // Nothing new, nothing to publish
+        assert!(table.update(id, progress, None).is_none());
+
+        let job = table.finish(id, Ok(())).unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        assert!(table.finish(id, Err(String::from("late"))).is_none());
+        assert!(table.update(id, Progress::default(), None).is_none());
+
+        let json = serde_json::to_value(&job).unwrap();
+        assert_eq!(json["kind"], "create");
+        assert_eq!(json["format"], "zip");
+        assert_eq!(json["entries"], 2);
+        assert_eq!(json["total_bytes"], 20);
+    }
+
+    #[test]
+    fn test_limits_and_pruning() {
+        let mut table = JobTable::default();
+        start(&mut table, 10, "/home/1/a.zip").unwrap();
+        assert!(start(&mut table, 11, "/home/1/a.zip").is_err());
+        start(&mut table, 10, "/home/1/b.zip").unwrap();
+        assert!(start(&mut table, 10, "/home/1/c.zip").is_err());
+        start(&mut table, 11, "/home/1/c.zip").unwrap();
+        start(&mut table, 12, "/home/1/d.zip").unwrap();
+        assert!(start(&mut table, 13, "/home/1/e.zip").is_err());
+        assert_eq!(table.jobs_of(10).len(), 2);
+
+        let mut table = JobTable::default();
+        let ids: Vec<u32> = (0..MAX_FINISHED_JOBS + 2)
+            .map(|i| {
+                let id = start(&mut table, 10, &format!("/home/1/{}.zip", i))
+                    .unwrap()
+                    .id;
+                table.finish(id, Err(String::from("full")));
+                id
+            })
+            .collect();
+        assert!(table.get(ids[0]).is_none());
+        assert!(table.get(ids[1]).is_none());
+        assert_eq!(table.get(ids[2]).unwrap().error, Some(String::from("full")));
+    }
+}
@@ -0,0 +1,687 @@
+//! Archive Service
+//!
+//! The ArchiveService creates and extracts zip and tar archives in the
+//! VFS, so apps can bundle a folder for export or unpack a downloaded
+//! package without holding either in their own heap. Archives and entries
+//! are streamed through VFS file handles a chunk at a time (see `io`);
+//! the service holds a few KiB and a 32 KiB inflate window per job.
+//!
+//! Jobs run in the background: a request is answered with the started job,
+//! and its progress is published as `archive/progress` events until it
+//! completes or fails (see [`jobs`]).
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - CREATE: Every listed entry streamed into the archive AND the archive
+//!   written to the end (index or end blocks) and closed
+//! - EXTRACT: Every entry written under the new destination directory
+//!   (zip entries matching their CRC-32)
+//!
+//! **Acceptable partial failure:**
+//! - CREATE fails → the partly written archive is deleted
+//! - EXTRACT fails → entries written so far are left in the destination,
+//!   and the job's error names the entry that failed
+//!
+//! **Forbidden:**
+//! - Reading or writing outside the requesting user's home directory
+//! - Extracting an entry outside the destination (absolute or `..` names)
+//! - Overwriting an existing archive or destination
+//! - Unbounded jobs, entries or extracted bytes (DoS vector, zip bombs)
+//!
+//! # Protocol
+//!
+//! - `MSG_ARCHIVE_CREATE (0x8C00)`: Archive a file or directory
+//! - `MSG_ARCHIVE_EXTRACT (0x8C02)`: Extract an archive into a new directory
+//! - `MSG_ARCHIVE_STATUS (0x8C04)`: Get the caller's jobs
+//!
+//! # Storage Access
+//!
+//! This service uses VFS IPC (async/await pattern, see
+//! [`zos_vfs::client::futures`]) with file handles for all archive I/O.
+//! All storage operations flow through the VFS service per Invariant 31.
+
+extern crate alloc;
+
+pub mod create;
+pub mod extract;
+pub(crate) mod inflate;
+pub(crate) mod io;
+pub mod jobs;
+pub mod tar;
+pub mod zip;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+use zos_ipc::events;
+use zos_vfs::client::futures::VfsExecutor;
+use zos_vfs::core::is_under;
+
+use crate::manifests::ARCHIVE_MANIFEST;
+use crate::response::JsonResponder;
+
+pub use io::Tracker;
+pub use jobs::{ArchiveJob, Format, JobKind, JobStatus, JobTable, Progress};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for archive service - re-exported from zos-ipc.
+pub mod archive_msg {
+    pub use zos_ipc::archive::*;
+}
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Maximum entries in an archive, created or extracted.
+pub const MAX_ENTRIES: usize = 10_000;
+
+/// Maximum content bytes in an archive, created or extracted. Keeps zip
+/// offsets within 32 bits and extraction within what the VFS can store.
+pub const MAX_TOTAL_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Maximum bytes one extracted entry may expand to.
+pub const MAX_ENTRY_BYTES: u64 = 128 * 1024 * 1024;
+
+/// Minimum time between progress events of a job (ms).
+const PROGRESS_INTERVAL_MS: u64 = 250;
+
+// =============================================================================
+// Request Types
+// =============================================================================
+
+/// Payload of MSG_ARCHIVE_CREATE.
+#[derive(Clone, Debug, Deserialize)]
+struct CreateRequest {
+    source: String,
+    archive: String,
+    #[serde(default)]
+    format: Option<Format>,
+    user_id: String,
+}
+
+/// Payload of MSG_ARCHIVE_EXTRACT.
+#[derive(Clone, Debug, Deserialize)]
+struct ExtractRequest {
+    archive: String,
+    dest: String,
+    user_id: String,
+}
+
+/// Payload of MSG_ARCHIVE_STATUS.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct StatusRequest {
+    #[serde(default)]
+    id: Option<u32>,
+}
+
+/// A job whose task is running.
+struct Running {
+    tracker: Rc<Tracker>,
+    /// Wall-clock time progress was last published (ms)
+    published_ms: u64,
+}
+
+/// Normalize a requested path and check it is inside `/home/{user_id}`.
+fn home_path(path: &str, user_id: &str) -> Result<String, String> {
+    let user_id = u128::from_str_radix(user_id, 16)
+        .map_err(|_| String::from("Archive requests require a hex user_id"))?;
+    let home = format!("/home/{}", user_id);
+    let path = zos_vfs::normalize_path(path).map_err(|e| format!("Invalid path: {:?}", e))?;
+    if path == home || !is_under(&path, &home) {
+        return Err(format!("{} is not inside {}", path, home));
+    }
+    Ok(path)
+}
+
+/// The format of an archive path, from its extension; zip by default.
+fn format_of(archive: &str) -> Format {
+    let lower = archive.to_ascii_lowercase();
+    if lower.ends_with(".tar") {
+        Format::Tar
+    } else {
+        Format::Zip
+    }
+}
+
+// =============================================================================
+// ArchiveService Application
+// =============================================================================
+
+/// ArchiveService - zip and tar archives streamed through the VFS
+pub struct ArchiveService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// All jobs
+    jobs: JobTable,
+    /// Jobs whose task is running, by job ID
+    running: BTreeMap<u32, Running>,
+    /// Job tasks, finishing with the job ID and result
+    tasks: VfsExecutor<(u32, Result<(), String>)>,
+    /// Wall-clock time of the last message (ms)
+    now_ms: u64,
+}
+
+impl Default for ArchiveService {
+    fn default() -> Self {
+        Self {
+            registered: false,
+            jobs: JobTable::default(),
+            running: BTreeMap::new(),
+            tasks: VfsExecutor::new(),
+            now_ms: 0,
+        }
+    }
+}
+
+impl ArchiveService {
+    /// Start a job's task.
+    fn start(
+        &mut self,
+        pid: u32,
+        kind: JobKind,
+        format: Option<Format>,
+        archive: String,
+        path: String,
+    ) -> Result<ArchiveJob, String> {
+        let job = self.jobs.start(
+            pid,
+            kind,
+            format,
+            archive.clone(),
+            path.clone(),
+            self.now_ms,
+        )?;
+        let tracker = Rc::new(Tracker::default());
+        self.running.insert(
+            job.id,
+            Running {
+                tracker: tracker.clone(),
+                published_ms: self.now_ms,
+            },
+        );
+        self.publish(&job);
+
+        let vfs = self.tasks.handle();
+        let id = job.id;
+        match (kind, format) {
+            (JobKind::Create, Some(format)) => self.tasks.spawn(async move {
+                (
+                    id,
+                    create::create(vfs, tracker, path, archive, format).await,
+                )
+            }),
+            _ => self
+                .tasks
+                .spawn(async move { (id, extract::extract(vfs, tracker, archive, path).await) }),
+        };
+        // Fails right away if the VFS can't be reached
+        self.finish_tasks();
+        Ok(self.jobs.get(id).cloned().unwrap_or(job))
+    }
+
+    /// Record every finished job.
+    fn finish_tasks(&mut self) {
+        while let Some((id, result)) = self.tasks.next_finished() {
+            let Some(running) = self.running.remove(&id) else {
+                continue;
+            };
+            self.jobs.update(
+                id,
+                running.tracker.progress.get(),
+                running.tracker.format.get(),
+            );
+            if let Err(e) = &result {
+                syscall::debug(&format!("ArchiveService: Job {} failed: {}", id, e));
+            }
+            if let Some(job) = self.jobs.finish(id, result) {
+                self.publish(&job);
+            }
+        }
+    }
+
+    /// Publish the progress of running jobs, at most every
+    /// [`PROGRESS_INTERVAL_MS`].
+    fn publish_progress(&mut self) {
+        let mut updated = Vec::new();
+        for (&id, running) in self.running.iter_mut() {
+            if self.now_ms < running.published_ms + PROGRESS_INTERVAL_MS {
+                continue;
+            }
+            let progress = running.tracker.progress.get();
+            if let Some(job) = self.jobs.update(id, progress, running.tracker.format.get()) {
+                running.published_ms = self.now_ms;
+                updated.push(job);
+            }
+        }
+        for job in updated {
+            self.publish(&job);
+        }
+    }
+
+    /// Publish a job on the event bus.
+    fn publish(&self, job: &ArchiveJob) {
+        let json =
+            serde_json::to_vec(&serde_json::json!({ "topic": "archive/progress", "payload": job }))
+                .unwrap_or_default();
+        if let Err(e) = syscall::send_named("events", events::MSG_EVENT_PUBLISH, &json) {
+            syscall::debug(&format!("ArchiveService: Publish failed: {}", e));
+        }
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_ARCHIVE_CREATE
+    fn handle_create(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = archive_msg::MSG_ARCHIVE_CREATE_RESPONSE;
+        let request: CreateRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid archive request: expected {\"source\": string, \"archive\": string, \"format\": string?, \"user_id\": hex}",
+                );
+            }
+        };
+        let result = self.create(msg.from_pid, request);
+        self.send_job_response(msg, tag, result)
+    }
+
+    fn create(&mut self, pid: u32, request: CreateRequest) -> Result<ArchiveJob, String> {
+        let source = home_path(&request.source, &request.user_id)?;
+        let archive = home_path(&request.archive, &request.user_id)?;
+        if is_under(&archive, &source) {
+            return Err(String::from("The archive cannot be inside its source"));
+        }
+        let format = request.format.unwrap_or_else(|| format_of(&archive));
+        self.start(pid, JobKind::Create, Some(format), archive, source)
+    }
+
+    /// Handle MSG_ARCHIVE_EXTRACT
+    fn handle_extract(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = archive_msg::MSG_ARCHIVE_EXTRACT_RESPONSE;
+        let request: ExtractRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid extract request: expected {\"archive\": string, \"dest\": string, \"user_id\": hex}",
+                );
+            }
+        };
+        let result = self.extract(msg.from_pid, request);
+        self.send_job_response(msg, tag, result)
+    }
+
+    fn extract(&mut self, pid: u32, request: ExtractRequest) -> Result<ArchiveJob, String> {
+        let archive = home_path(&request.archive, &request.user_id)?;
+        let dest = home_path(&request.dest, &request.user_id)?;
+        if is_under(&archive, &dest) {
+            return Err(String::from("The archive cannot be inside its destination"));
+        }
+        self.start(pid, JobKind::Extract, None, archive, dest)
+    }
+
+    /// Handle MSG_ARCHIVE_STATUS
+    fn handle_status(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = archive_msg::MSG_ARCHIVE_STATUS_RESPONSE;
+        let request: StatusRequest = if msg.data.is_empty() {
+            StatusRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid status request: expected {\"id\": u32?}",
+                    );
+                }
+            }
+        };
+        let mut jobs = self.jobs.jobs_of(msg.from_pid);
+        if let Some(id) = request.id {
+            jobs.retain(|job| job.id == id);
+        }
+        let json = serde_json::to_vec(&serde_json::json!({ "jobs": jobs })).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Handle a VFS response to one of our jobs. Returns false if the
+    /// message isn't one.
+    fn handle_vfs_response(&mut self, msg: &Message) -> bool {
+        if !self.tasks.on_message(msg.tag, &msg.data) {
+            return false;
+        }
+        self.finish_tasks();
+        self.publish_progress();
+        true
+    }
+
+    // =========================================================================
+    // Response helpers
+    // =========================================================================
+
+    fn send_job_response(
+        &self,
+        msg: &Message,
+        tag: u32,
+        result: Result<ArchiveJob, String>,
+    ) -> Result<(), AppError> {
+        match result {
+            Ok(job) => {
+                let json = serde_json::to_vec(&job).unwrap_or_default();
+                self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+}
+
+impl JsonResponder for ArchiveService {
+    const SERVICE_NAME: &'static str = "ArchiveService";
+}
+
+impl ZeroApp for ArchiveService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &ARCHIVE_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("ArchiveService starting (PID {})", ctx.pid));
+        self.now_ms = ctx.wallclock_ms;
+
+        // Register with init as "archive" service
+        let service_name = "archive";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("ArchiveService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        self.now_ms = ctx.wallclock_ms;
+
+        // VFS responses (Invariant 31 compliant - storage via VFS IPC)
+        if self.handle_vfs_response(&msg) {
+            return Ok(());
+        }
+
+        match msg.tag {
+            // Archive service protocol
+            archive_msg::MSG_ARCHIVE_CREATE => self.handle_create(&msg),
+            archive_msg::MSG_ARCHIVE_EXTRACT => self.handle_extract(&msg),
+            archive_msg::MSG_ARCHIVE_STATUS => self.handle_status(&msg),
+
+            _ => {
+                syscall::debug(&format!(
+                    "ArchiveService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("ArchiveService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+    use zos_vfs::ipc::vfs_msg;
+    use zos_vfs::Inode;
+
+    fn request(service: &mut ArchiveService, tag: u32, pid: u32, json: &str) {
+        let msg = mock_message(tag, pid, json.as_bytes().to_vec());
+        match tag {
+            archive_msg::MSG_ARCHIVE_CREATE => service.handle_create(&msg),
+            archive_msg::MSG_ARCHIVE_EXTRACT => service.handle_extract(&msg),
+            _ => service.handle_status(&msg),
+        }
+        .unwrap();
+    }
+
+    fn vfs_ok(service: &mut ArchiveService, tag: u32, value: serde_json::Value) {
+        let json = serde_json::to_vec(&serde_json::json!({ "result": { "Ok": value } })).unwrap();
+        assert!(service.handle_vfs_response(&mock_message(tag, 4, json)));
+    }
+
+    fn file_inode(path: &str, size: u64) -> serde_json::Value {
+        let (parent, name) = path.rsplit_once('/').unwrap();
+        let inode = Inode::new_file(
+            String::from(path),
+            String::from(parent),
+            String::from(name),
+            None,
+            size,
+            None,
+            1_709_214_330_000,
+        );
+        serde_json::to_value(inode).unwrap()
+    }
+
+    fn only_job(service: &ArchiveService) -> ArchiveJob {
+        let jobs = service.jobs.jobs_of(10);
+        assert_eq!(jobs.len(), 1);
+        jobs[0].clone()
+    }
+
+    #[test]
+    fn test_create_zip_of_a_file() {
+        let mut service = ArchiveService::default();
+        request(
+            &mut service,
+            archive_msg::MSG_ARCHIVE_CREATE,
+            10,
+            r#"{"source":"/home/42/a.txt","archive":"/home/42/out/a.zip","user_id":"2a"}"#,
+        );
+        let job = only_job(&service);
+        assert_eq!(job.kind, JobKind::Create);
+        assert_eq!(job.format, Some(Format::Zip));
+        assert_eq!(job.status, JobStatus::Running);
+
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_EXISTS_RESPONSE, false.into());
+        vfs_ok(
+            &mut service,
+            vfs_msg::MSG_VFS_STAT_RESPONSE,
+            file_inode("/home/42/a.txt", 2),
+        );
+        // Archive, then source opened
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_OPEN_RESPONSE, 1.into());
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_OPEN_RESPONSE, 2.into());
+        vfs_ok(
+            &mut service,
+            vfs_msg::MSG_VFS_READ_AT_RESPONSE,
+            serde_json::json!([104, 105]),
+        );
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_CLOSE_RESPONSE, ().into());
+        // Header and content, the patched sizes, then the central directory
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_WRITE_AT_RESPONSE, 39.into());
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_WRITE_AT_RESPONSE, 39.into());
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_WRITE_AT_RESPONSE, 128.into());
+        assert_eq!(only_job(&service).status, JobStatus::Running);
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_CLOSE_RESPONSE, ().into());
+
+        let job = only_job(&service);
+        assert_eq!(job.status, JobStatus::Completed, "{:?}", job.error);
+        assert_eq!(
+            job.progress,
+            Progress {
+                entries: 1,
+                bytes: 2,
+                total_bytes: Some(2),
+            }
+        );
+        assert!(service.running.is_empty());
+        assert_eq!(service.tasks.running(), 0);
+    }
+
+    #[test]
+    fn test_failed_create_deletes_the_archive() {
+        let mut service = ArchiveService::default();
+        request(
+            &mut service,
+            archive_msg::MSG_ARCHIVE_CREATE,
+            10,
+            r#"{"source":"/home/42/a.txt","archive":"/home/42/a.tar","user_id":"2a"}"#,
+        );
+        assert_eq!(only_job(&service).format, Some(Format::Tar));
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_EXISTS_RESPONSE, false.into());
+        vfs_ok(
+            &mut service,
+            vfs_msg::MSG_VFS_STAT_RESPONSE,
+            file_inode("/home/42/a.txt", 4),
+        );
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_OPEN_RESPONSE, 1.into());
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_OPEN_RESPONSE, 2.into());
+        // The file shrank since it was listed
+        vfs_ok(
+            &mut service,
+            vfs_msg::MSG_VFS_READ_AT_RESPONSE,
+            serde_json::json!([]),
+        );
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_CLOSE_RESPONSE, ().into());
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_CLOSE_RESPONSE, ().into());
+        assert_eq!(only_job(&service).status, JobStatus::Running);
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_UNLINK_RESPONSE, ().into());
+
+        let job = only_job(&service);
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.error.unwrap().contains("changed while archiving"));
+    }
+
+    #[test]
+    fn test_extract_rejects_unknown_formats() {
+        let mut service = ArchiveService::default();
+        request(
+            &mut service,
+            archive_msg::MSG_ARCHIVE_EXTRACT,
+            10,
+            r#"{"archive":"/home/42/a.zip","dest":"/home/42/a","user_id":"2a"}"#,
+        );
+        vfs_ok(
+            &mut service,
+            vfs_msg::MSG_VFS_STAT_RESPONSE,
+            file_inode("/home/42/a.zip", 5),
+        );
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_EXISTS_RESPONSE, false.into());
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_OPEN_RESPONSE, 1.into());
+        vfs_ok(
+            &mut service,
+            vfs_msg::MSG_VFS_READ_AT_RESPONSE,
+            serde_json::json!(b"hello"),
+        );
+        vfs_ok(&mut service, vfs_msg::MSG_VFS_CLOSE_RESPONSE, ().into());
+
+        let job = only_job(&service);
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.error.as_deref(), Some("Not a zip or tar archive"));
+    }
+
+    #[test]
+    fn test_rejected_requests() {
+        let mut service = ArchiveService::default();
+        let create = archive_msg::MSG_ARCHIVE_CREATE;
+        let extract = archive_msg::MSG_ARCHIVE_EXTRACT;
+        // Outside the user's home, or the home itself
+        request(
+            &mut service,
+            create,
+            10,
+            r#"{"source":"/home/43/a","archive":"/home/42/a.zip","user_id":"2a"}"#,
+        );
+        request(
+            &mut service,
+            create,
+            10,
+            r#"{"source":"/home/42","archive":"/home/42/a.zip","user_id":"2a"}"#,
+        );
+        request(
+            &mut service,
+            extract,
+            10,
+            r#"{"archive":"/home/42/a.zip","dest":"/home/42/../43/a","user_id":"2a"}"#,
+        );
+        // Archive inside what it archives or extracts to
+        request(
+            &mut service,
+            create,
+            10,
+            r#"{"source":"/home/42/a","archive":"/home/42/a/a.zip","user_id":"2a"}"#,
+        );
+        request(
+            &mut service,
+            extract,
+            10,
+            r#"{"archive":"/home/42/a/a.zip","dest":"/home/42/a","user_id":"2a"}"#,
+        );
+        // Not a hex user ID
+        request(
+            &mut service,
+            create,
+            10,
+            r#"{"source":"/home/42/a","archive":"/home/42/a.zip","user_id":"xyz"}"#,
+        );
+        request(&mut service, create, 10, r#"{"source":"/home/42/a"}"#);
+        assert!(service.jobs.jobs_of(10).is_empty());
+        assert!(service.running.is_empty());
+    }
+
+    #[test]
+    fn test_home_paths() {
+        assert_eq!(
+            home_path("/home/42/docs/../a.zip", "2a").unwrap(),
+            "/home/42/a.zip"
+        );
+        assert!(home_path("/home/420/a.zip", "2a").is_err());
+        assert!(home_path("home/42/a.zip", "2a").is_err());
+        assert_eq!(format_of("/home/42/A.TAR"), Format::Tar);
+        assert_eq!(format_of("/home/42/a.tar.gz"), Format::Zip);
+    }
+}
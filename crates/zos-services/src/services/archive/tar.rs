@@ -0,0 +1,343 @@
+//! Tar archives (POSIX ustar)
+//!
+//! A tar file is a sequence of 512-byte blocks: a header per entry, the
+//! entry's content padded to a whole block, and two zero blocks at the end.
+//!
+//! Written archives are ustar. Paths that don't fit the ustar name and
+//! prefix fields get a pax extended header (`x`) carrying `path=`.
+//! Reading also understands GNU long names (`L`); global pax headers,
+//! links and devices are skipped.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Tar block size.
+pub const BLOCK: usize = 512;
+
+/// What an entry is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    /// Anything else (links, devices, FIFOs): content is skipped
+    Other,
+    /// Pax extended header for the next entry
+    PaxHeader,
+    /// Pax global header
+    PaxGlobal,
+    /// GNU long name for the next entry
+    LongName,
+}
+
+/// A parsed header block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub path: String,
+    pub kind: EntryKind,
+    /// Content size, in bytes
+    pub size: u64,
+}
+
+/// Bytes of padding after `size` bytes of content.
+pub fn padding(size: u64) -> usize {
+    (BLOCK - (size % BLOCK as u64) as usize) % BLOCK
+}
+
+/// Header blocks for an entry: one ustar header, preceded by a pax header
+/// and its data if the path needs one. Directory paths get a trailing `/`.
+pub fn entry_headers(path: &str, kind: EntryKind, size: u64, mtime_secs: u64) -> Vec<u8> {
+    let mut path = String::from(path.trim_end_matches('/'));
+    if kind == EntryKind::Directory {
+        path.push('/');
+    }
+    let mut out = Vec::new();
+    let (name, prefix) = match split_ustar_path(&path) {
+        Some(split) => split,
+        None => {
+            let record = pax_record("path", &path);
+            let pax_name = format!("PaxHeaders/{}", last_component(&path));
+            out.extend_from_slice(&ustar_header(
+                truncate(&pax_name, 100),
+                "",
+                b'x',
+                record.len() as u64,
+                mtime_secs,
+            ));
+            out.extend_from_slice(record.as_bytes());
+            out.resize(out.len() + padding(record.len() as u64), 0);
+            // Readers without pax support see a truncated name
+            (truncate(&path, 100), "")
+        }
+    };
+    let typeflag = if kind == EntryKind::Directory {
+        b'5'
+    } else {
+        b'0'
+    };
+    let size = if kind == EntryKind::Directory {
+        0
+    } else {
+        size
+    };
+    out.extend_from_slice(&ustar_header(name, prefix, typeflag, size, mtime_secs));
+    out
+}
+
+/// The two zero blocks that end an archive.
+pub fn end_of_archive() -> Vec<u8> {
+    vec![0u8; 2 * BLOCK]
+}
+
+/// Parse a header block. Returns `Ok(None)` for a zero block (end of
+/// archive).
+pub fn parse_header(block: &[u8; BLOCK]) -> Result<Option<Header>, String> {
+    if block.iter().all(|&b| b == 0) {
+        return Ok(None);
+    }
+    let stored = octal(&block[148..156]).ok_or("Invalid tar header checksum field")?;
+    let sum: u64 = block
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| {
+            if (148..156).contains(&i) {
+                b' ' as u64
+            } else {
+                b as u64
+            }
+        })
+        .sum();
+    if sum != stored {
+        return Err(String::from("Tar header checksum mismatch"));
+    }
+
+    let size = octal(&block[124..136]).ok_or("Invalid tar entry size")?;
+    let name = field(&block[0..100]);
+    let path = if &block[257..262] == b"ustar" {
+        let prefix = field(&block[345..500]);
+        if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        }
+    } else {
+        name
+    };
+    let kind = match block[156] {
+        b'0' | 0 | b'7' => {
+            if path.ends_with('/') {
+                EntryKind::Directory
+            } else {
+                EntryKind::File
+            }
+        }
+        b'5' => EntryKind::Directory,
+        b'x' => EntryKind::PaxHeader,
+        b'g' => EntryKind::PaxGlobal,
+        b'L' => EntryKind::LongName,
+        _ => EntryKind::Other,
+    };
+    Ok(Some(Header { path, kind, size }))
+}
+
+/// The `path` of a pax extended header's records, if it has one.
+pub fn pax_path(data: &[u8]) -> Option<String> {
+    let mut rest = data;
+    let mut path = None;
+    while !rest.is_empty() {
+        let space = rest.iter().position(|&b| b == b' ')?;
+        let len: usize = core::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        if len <= space || len > rest.len() {
+            return None;
+        }
+        let record = &rest[space + 1..len];
+        let record = record.strip_suffix(b"\n").unwrap_or(record);
+        if let Some(value) = record.strip_prefix(b"path=") {
+            path = Some(String::from_utf8_lossy(value).into_owned());
+        }
+        rest = &rest[len..];
+    }
+    path
+}
+
+/// Split a path into ustar `name` (at most 100 bytes) and `prefix` (at
+/// most 155 bytes) fields at a `/`.
+fn split_ustar_path(path: &str) -> Option<(&str, &str)> {
+    if path.len() <= 100 {
+        return Some((path, ""));
+    }
+    // A directory's trailing slash stays with the name
+    let search_end = path.len().saturating_sub(1).min(156);
+    path[..search_end]
+        .rmatch_indices('/')
+        .map(|(i, _)| (&path[i + 1..], &path[..i]))
+        .find(|(name, prefix)| name.len() <= 100 && prefix.len() <= 155 && !prefix.is_empty())
+}
+
+fn ustar_header(name: &str, prefix: &str, typeflag: u8, size: u64, mtime_secs: u64) -> [u8; BLOCK] {
+    let mut block = [0u8; BLOCK];
+    block[..name.len()].copy_from_slice(name.as_bytes());
+    let mode = if typeflag == b'5' { 0o755 } else { 0o644 };
+    put_octal(&mut block[100..108], mode);
+    put_octal(&mut block[108..116], 0);
+    put_octal(&mut block[116..124], 0);
+    put_octal(&mut block[124..136], size);
+    put_octal(&mut block[136..148], mtime_secs);
+    block[156] = typeflag;
+    block[257..263].copy_from_slice(b"ustar\0");
+    block[263..265].copy_from_slice(b"00");
+    block[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    block[148..156].fill(b' ');
+    let sum: u64 = block.iter().map(|&b| b as u64).sum();
+    put_octal(&mut block[148..155], sum);
+    block
+}
+
+/// A pax record: "{len} {key}={value}\n", where len counts itself.
+fn pax_record(key: &str, value: &str) -> String {
+    let body = key.len() + value.len() + 3;
+    let mut len = body + 1;
+    while len != body + format!("{}", len).len() {
+        len = body + format!("{}", len).len();
+    }
+    format!("{} {}={}\n", len, key, value)
+}
+
+/// Write `value` as zero-padded octal, NUL-terminated.
+fn put_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let text = format!("{:0width$o}", value, width = digits);
+    let text = &text.as_bytes()[text.len().saturating_sub(digits)..];
+    field[..digits].copy_from_slice(text);
+    field[digits] = 0;
+}
+
+/// Read an octal field, or a base-256 one (high bit set) as GNU tar
+/// writes for large sizes.
+fn octal(field: &[u8]) -> Option<u64> {
+    if field.first().is_some_and(|&b| b & 0x80 != 0) {
+        let mut value = (field[0] & 0x7f) as u64;
+        for &b in &field[1..] {
+            value = value.checked_mul(256)?.checked_add(b as u64)?;
+        }
+        return Some(value);
+    }
+    let text = core::str::from_utf8(field).ok()?;
+    let text = text.trim_matches(|c| c == ' ' || c == '\0');
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+/// A NUL-terminated text field.
+fn field(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// The longest prefix of `s` of at most `max` bytes that ends on a
+/// character boundary.
+fn truncate(s: &str, max: usize) -> &str {
+    let mut end = s.len().min(max);
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+fn last_component(path: &str) -> &str {
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(bytes: &[u8]) -> [u8; BLOCK] {
+        bytes.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_short_paths() {
+        let headers = entry_headers("docs/readme.txt", EntryKind::File, 1234, 1_700_000_000);
+        assert_eq!(headers.len(), BLOCK);
+        let header = parse_header(&block(&headers)).unwrap().unwrap();
+        assert_eq!(
+            header,
+            Header {
+                path: String::from("docs/readme.txt"),
+                kind: EntryKind::File,
+                size: 1234,
+            }
+        );
+        assert_eq!(&headers[124..136], b"00000002322\0");
+
+        let dir = entry_headers("docs", EntryKind::Directory, 99, 0);
+        let header = parse_header(&block(&dir)).unwrap().unwrap();
+        assert_eq!(header.path, "docs/");
+        assert_eq!(header.kind, EntryKind::Directory);
+        assert_eq!(header.size, 0);
+
+        assert_eq!(parse_header(&[0u8; BLOCK]).unwrap(), None);
+        assert_eq!(padding(0), 0);
+        assert_eq!(padding(1), 511);
+        assert_eq!(padding(512), 0);
+    }
+
+    #[test]
+    fn test_long_paths_use_prefix_or_pax() {
+        let long = format!("{}/{}", "d".repeat(120), "f".repeat(50));
+        let headers = entry_headers(&long, EntryKind::File, 1, 0);
+        assert_eq!(headers.len(), BLOCK);
+        assert_eq!(parse_header(&block(&headers)).unwrap().unwrap().path, long);
+
+        let very_long = format!("{}/{}", "d".repeat(300), "f".repeat(150));
+        let headers = entry_headers(&very_long, EntryKind::File, 1, 0);
+        assert_eq!(headers.len(), 3 * BLOCK);
+        let pax = parse_header(&block(&headers[..BLOCK])).unwrap().unwrap();
+        assert_eq!(pax.kind, EntryKind::PaxHeader);
+        let data = &headers[BLOCK..BLOCK + pax.size as usize];
+        assert_eq!(pax_path(data), Some(very_long));
+        let entry = parse_header(&block(&headers[2 * BLOCK..]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.kind, EntryKind::File);
+    }
+
+    #[test]
+    fn test_pax_records() {
+        let record = pax_record("path", "a");
+        assert_eq!(record, "9 path=a\n");
+        assert_eq!(record.len(), 9);
+        // Length crossing a digit boundary
+        let record = pax_record("path", &"x".repeat(91));
+        assert_eq!(record.len(), 101);
+        assert!(record.starts_with("101 "));
+
+        assert_eq!(
+            pax_path(b"20 mtime=1700000000\n11 path=ab\n"),
+            Some(String::from("ab"))
+        );
+        assert_eq!(pax_path(b"99 path=ab\n"), None);
+    }
+
+    #[test]
+    fn test_rejects_bad_checksum() {
+        let mut headers = entry_headers("a.txt", EntryKind::File, 1, 0);
+        headers[0] = b'b';
+        assert!(parse_header(&block(&headers)).is_err());
+
+        // GNU base-256 sizes
+        assert_eq!(
+            octal(&[0x80, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0]),
+            Some(1 << 32)
+        );
+        assert_eq!(octal(b"   17 \0"), Some(15));
+    }
+}
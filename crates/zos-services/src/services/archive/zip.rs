@@ -0,0 +1,417 @@
+//! Zip archives
+//!
+//! A zip file is each entry's local header and data, then a central
+//! directory listing every entry, then an end-of-central-directory record
+//! (EOCD) pointing at it. Readers start from the EOCD, so extraction reads
+//! the tail of the archive first and then each entry where the central
+//! directory says it is.
+//!
+//! Written archives store entries uncompressed (method 0): the local
+//! header is written with a zero CRC and sizes, and patched once the
+//! entry's data has been streamed (see [`LOCAL_CRC_OFFSET`]). Reading
+//! supports stored and deflated (method 8) entries. ZIP64 and encrypted
+//! archives are refused.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::services::time::tz::civil_from_days;
+
+/// Compression methods.
+pub const METHOD_STORED: u16 = 0;
+pub const METHOD_DEFLATED: u16 = 8;
+
+const LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_SIGNATURE: u32 = 0x0201_4b50;
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+
+/// Size of a local header without its name and extra field.
+pub const LOCAL_HEADER_LEN: usize = 30;
+
+/// Size of the EOCD record without its comment.
+pub const EOCD_LEN: usize = 22;
+
+/// How far from the end of the file the EOCD can start: the record plus
+/// the longest comment.
+pub const MAX_EOCD_SEARCH: usize = EOCD_LEN + 0xffff;
+
+/// Offset of the CRC and the two sizes in a local header; written as 12
+/// bytes by [`local_sizes`].
+pub const LOCAL_CRC_OFFSET: u64 = 14;
+
+/// General purpose flag: names are UTF-8.
+const FLAG_UTF8: u16 = 1 << 11;
+
+/// General purpose flag: entry is encrypted.
+const FLAG_ENCRYPTED: u16 = 1;
+
+/// "Version made by": Unix, spec 2.0.
+const VERSION_MADE_BY: u16 = (3 << 8) | 20;
+const VERSION_NEEDED: u16 = 20;
+
+/// An entry of the central directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZipEntry {
+    pub name: String,
+    pub method: u16,
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub size: u64,
+    /// Offset of the entry's local header
+    pub local_offset: u64,
+}
+
+impl ZipEntry {
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+}
+
+/// Where the central directory is, from the EOCD.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CentralDirectory {
+    pub entries: u16,
+    pub size: u64,
+    pub offset: u64,
+}
+
+// =============================================================================
+// CRC-32
+// =============================================================================
+
+/// CRC-32 (IEEE 802.3), as zip uses.
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32(u32);
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Crc32(0xffff_ffff)
+    }
+}
+
+impl Crc32 {
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            let mut c = (self.0 ^ b as u32) & 0xff;
+            for _ in 0..8 {
+                c = if c & 1 != 0 {
+                    0xedb8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            self.0 = c ^ (self.0 >> 8);
+        }
+    }
+
+    pub fn finish(self) -> u32 {
+        !self.0
+    }
+}
+
+// =============================================================================
+// Writing
+// =============================================================================
+
+/// MS-DOS (time, date) of a wall-clock time; zip can't record dates
+/// before 1980.
+pub fn dos_datetime(wallclock_ms: u64) -> (u16, u16) {
+    let secs = wallclock_ms / 1000;
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    if year < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let of_day = secs % 86_400;
+    let time = ((of_day / 3600) << 11) | (((of_day / 60) % 60) << 5) | ((of_day % 60) / 2);
+    let date = (((year - 1980).min(127) as u32) << 9) | (month << 5) | day;
+    (time as u16, date as u16)
+}
+
+/// A local header with a zero CRC and sizes, to be patched with
+/// [`local_sizes`] once the data is written.
+pub fn local_header(name: &str, datetime: (u16, u16)) -> Vec<u8> {
+    let mut out = Vec::with_capacity(LOCAL_HEADER_LEN + name.len());
+    put32(&mut out, LOCAL_SIGNATURE);
+    put16(&mut out, VERSION_NEEDED);
+    put16(&mut out, FLAG_UTF8);
+    put16(&mut out, METHOD_STORED);
+    put16(&mut out, datetime.0);
+    put16(&mut out, datetime.1);
+    out.extend_from_slice(&[0u8; 12]);
+    put16(&mut out, name.len() as u16);
+    put16(&mut out, 0);
+    out.extend_from_slice(name.as_bytes());
+    out
+}
+
+/// The CRC and sizes of a stored entry, for [`LOCAL_CRC_OFFSET`].
+pub fn local_sizes(crc32: u32, size: u32) -> [u8; 12] {
+    let mut out = [0u8; 12];
+    out[0..4].copy_from_slice(&crc32.to_le_bytes());
+    out[4..8].copy_from_slice(&size.to_le_bytes());
+    out[8..12].copy_from_slice(&size.to_le_bytes());
+    out
+}
+
+/// A central directory record for a stored entry.
+pub fn central_header(
+    name: &str,
+    datetime: (u16, u16),
+    crc32: u32,
+    size: u32,
+    local_offset: u32,
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(46 + name.len());
+    put32(&mut out, CENTRAL_SIGNATURE);
+    put16(&mut out, VERSION_MADE_BY);
+    put16(&mut out, VERSION_NEEDED);
+    put16(&mut out, FLAG_UTF8);
+    put16(&mut out, METHOD_STORED);
+    put16(&mut out, datetime.0);
+    put16(&mut out, datetime.1);
+    put32(&mut out, crc32);
+    put32(&mut out, size);
+    put32(&mut out, size);
+    put16(&mut out, name.len() as u16);
+    // Extra field, comment, disk number, internal attributes
+    put16(&mut out, 0);
+    put16(&mut out, 0);
+    put16(&mut out, 0);
+    put16(&mut out, 0);
+    // External attributes: Unix mode in the high half
+    let mode: u32 = if name.ends_with('/') {
+        0o040755
+    } else {
+        0o100644
+    };
+    put32(&mut out, mode << 16);
+    put32(&mut out, local_offset);
+    out.extend_from_slice(name.as_bytes());
+    out
+}
+
+/// The EOCD record.
+pub fn end_of_central_directory(entries: u16, size: u32, offset: u32) -> Vec<u8> {
+    let mut out = Vec::with_capacity(EOCD_LEN);
+    put32(&mut out, EOCD_SIGNATURE);
+    put16(&mut out, 0);
+    put16(&mut out, 0);
+    put16(&mut out, entries);
+    put16(&mut out, entries);
+    put32(&mut out, size);
+    put32(&mut out, offset);
+    put16(&mut out, 0);
+    out
+}
+
+// =============================================================================
+// Reading
+// =============================================================================
+
+/// Whether `bytes` start like a zip file (an entry, or an empty archive).
+pub fn is_zip(bytes: &[u8]) -> bool {
+    bytes.len() >= 4 && matches!(le32(bytes, 0), LOCAL_SIGNATURE | EOCD_SIGNATURE)
+}
+
+/// Find the EOCD in the last bytes of an archive (`tail`, at most
+/// [`MAX_EOCD_SEARCH`] bytes).
+pub fn find_end_of_central_directory(tail: &[u8]) -> Result<CentralDirectory, String> {
+    if tail.len() < EOCD_LEN {
+        return Err(String::from("Not a zip archive"));
+    }
+    // The last signature whose comment length reaches the end exactly
+    let at = (0..=tail.len() - EOCD_LEN)
+        .rev()
+        .find(|&i| {
+            le32(tail, i) == EOCD_SIGNATURE
+                && i + EOCD_LEN + le16(tail, i + 20) as usize == tail.len()
+        })
+        .ok_or_else(|| String::from("Not a zip archive"))?;
+    let record = &tail[at..];
+    if le16(record, 4) != 0 || le16(record, 6) != 0 {
+        return Err(String::from("Multi-disk zip archives are not supported"));
+    }
+    let entries = le16(record, 10);
+    let size = le32(record, 12);
+    let offset = le32(record, 16);
+    if entries == 0xffff || size == 0xffff_ffff || offset == 0xffff_ffff {
+        return Err(String::from("ZIP64 archives are not supported"));
+    }
+    Ok(CentralDirectory {
+        entries,
+        size: size as u64,
+        offset: offset as u64,
+    })
+}
+
+/// Parse the central directory.
+pub fn parse_central_directory(bytes: &[u8], entries: u16) -> Result<Vec<ZipEntry>, String> {
+    let mut out = Vec::with_capacity(entries as usize);
+    let mut at = 0;
+    for _ in 0..entries {
+        if at + 46 > bytes.len() || le32(bytes, at) != CENTRAL_SIGNATURE {
+            return Err(String::from("Corrupt zip central directory"));
+        }
+        let flags = le16(bytes, at + 8);
+        let name_len = le16(bytes, at + 28) as usize;
+        let extra_len = le16(bytes, at + 30) as usize;
+        let comment_len = le16(bytes, at + 32) as usize;
+        let end = at + 46 + name_len + extra_len + comment_len;
+        if end > bytes.len() {
+            return Err(String::from("Corrupt zip central directory"));
+        }
+        if flags & FLAG_ENCRYPTED != 0 {
+            return Err(String::from("Encrypted zip archives are not supported"));
+        }
+        let compressed_size = le32(bytes, at + 20);
+        let size = le32(bytes, at + 24);
+        let local_offset = le32(bytes, at + 42);
+        if [compressed_size, size, local_offset].contains(&0xffff_ffff) {
+            return Err(String::from("ZIP64 archives are not supported"));
+        }
+        out.push(ZipEntry {
+            name: String::from_utf8_lossy(&bytes[at + 46..at + 46 + name_len]).into_owned(),
+            method: le16(bytes, at + 10),
+            crc32: le32(bytes, at + 16),
+            compressed_size: compressed_size as u64,
+            size: size as u64,
+            local_offset: local_offset as u64,
+        });
+        at = end;
+    }
+    Ok(out)
+}
+
+/// Offset of an entry's data from its local header, given the header's
+/// first [`LOCAL_HEADER_LEN`] bytes.
+pub fn local_data_offset(header: &[u8]) -> Result<u64, String> {
+    if header.len() < LOCAL_HEADER_LEN || le32(header, 0) != LOCAL_SIGNATURE {
+        return Err(String::from("Corrupt zip local header"));
+    }
+    let name_len = le16(header, 26) as u64;
+    let extra_len = le16(header, 28) as u64;
+    Ok(LOCAL_HEADER_LEN as u64 + name_len + extra_len)
+}
+
+/// Check an entry's method.
+pub fn check_method(entry: &ZipEntry) -> Result<(), String> {
+    match entry.method {
+        METHOD_STORED | METHOD_DEFLATED => Ok(()),
+        method => Err(format!(
+            "{}: compression method {} is not supported",
+            entry.name, method
+        )),
+    }
+}
+
+fn put16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn le16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn le32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        let mut crc = Crc32::default();
+        crc.update(b"123456789");
+        assert_eq!(crc.finish(), 0xcbf4_3926);
+        assert_eq!(Crc32::default().finish(), 0);
+    }
+
+    #[test]
+    fn test_dos_datetime() {
+        // 2024-02-29 13:45:30 UTC
+        let (time, date) = dos_datetime(1_709_214_330_000);
+        assert_eq!(time, (13 << 11) | (45 << 5) | 15);
+        assert_eq!(date, (44 << 9) | (2 << 5) | 29);
+        assert_eq!(dos_datetime(0), (0, (1 << 5) | 1));
+    }
+
+    #[test]
+    fn test_write_then_read() {
+        // One stored file, as the service writes it
+        let when = dos_datetime(1_709_214_330_000);
+        let mut archive = local_header("dir/a.txt", when);
+        let mut crc = Crc32::default();
+        crc.update(b"hi");
+        let at = LOCAL_CRC_OFFSET as usize;
+        archive[at..at + 12].copy_from_slice(&local_sizes(crc.finish(), 2));
+        archive.extend_from_slice(b"hi");
+        let central_at = archive.len();
+        let central = central_header("dir/a.txt", when, crc.finish(), 2, 0);
+        archive.extend_from_slice(&central);
+        archive.extend_from_slice(&end_of_central_directory(
+            1,
+            central.len() as u32,
+            central_at as u32,
+        ));
+        assert!(is_zip(&archive));
+
+        let dir = find_end_of_central_directory(&archive).unwrap();
+        assert_eq!(
+            dir,
+            CentralDirectory {
+                entries: 1,
+                size: central.len() as u64,
+                offset: central_at as u64,
+            }
+        );
+        let start = dir.offset as usize;
+        let entries =
+            parse_central_directory(&archive[start..start + dir.size as usize], 1).unwrap();
+        assert_eq!(
+            entries,
+            vec![ZipEntry {
+                name: String::from("dir/a.txt"),
+                method: METHOD_STORED,
+                crc32: 0xd893_2aac,
+                compressed_size: 2,
+                size: 2,
+                local_offset: 0,
+            }]
+        );
+        let data_at = local_data_offset(&archive).unwrap() as usize;
+        assert_eq!(&archive[data_at..data_at + 2], b"hi");
+        assert!(check_method(&entries[0]).is_ok());
+    }
+
+    #[test]
+    fn test_eocd_search_and_limits() {
+        // An empty archive with a comment that contains a fake signature
+        let mut archive = end_of_central_directory(0, 0, 0);
+        archive[20] = 6;
+        archive.extend_from_slice(b"PK\x05\x06!!");
+        let dir = find_end_of_central_directory(&archive).unwrap();
+        assert_eq!(dir.entries, 0);
+
+        assert!(find_end_of_central_directory(b"not a zip file at all!!").is_err());
+        assert!(find_end_of_central_directory(&end_of_central_directory(0xffff, 0, 0)).is_err());
+        assert!(parse_central_directory(&[0u8; 46], 1).is_err());
+        assert!(!is_zip(b"PK"));
+
+        let entry = ZipEntry {
+            name: String::from("x"),
+            method: 12,
+            crc32: 0,
+            compressed_size: 0,
+            size: 0,
+            local_offset: 0,
+        };
+        assert!(check_method(&entry).is_err());
+    }
+}
@@ -0,0 +1,610 @@
+//! iCalendar (ICS) import
+//!
+//! Reads the `VEVENT`s of an RFC 5545 calendar into event fields. Covers
+//! what calendar exports commonly contain: summary, description, location,
+//! start and end (UTC, with a `TZID` from the built-in zone table, or
+//! all-day dates), `DURATION`, the `RRULE` subset of [`super::recur`],
+//! `EXDATE` and `VALARM`s that trigger before the start.
+//!
+//! Anything else is skipped with a warning rather than failing the import:
+//! a recurrence rule using unsupported parts imports the first occurrence
+//! only, and modified occurrences (`RECURRENCE-ID`) and cancelled events
+//! are left out.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::recur::{local_to_utc, Frequency, Recurrence, MS_PER_DAY, MS_PER_MINUTE};
+use super::store::{EventFields, MAX_EVENTS, MAX_EXDATES, MAX_REMINDERS};
+use crate::services::time::tz::{days_from_civil, TimeZone};
+
+/// Largest calendar accepted, in bytes (Rule 11: resource limits)
+pub const MAX_ICS_SIZE: usize = 256 * 1024;
+
+/// Most warnings reported per import
+const MAX_WARNINGS: usize = 32;
+
+/// Events read from a calendar.
+#[derive(Debug, Default)]
+pub struct ParsedCalendar {
+    /// UID and fields of each event
+    pub events: Vec<(String, EventFields)>,
+    /// Events left out
+    pub skipped: u32,
+    /// What was skipped or approximated, and why
+    pub warnings: Vec<String>,
+}
+
+impl ParsedCalendar {
+    fn warn(&mut self, warning: String) {
+        if self.warnings.len() < MAX_WARNINGS {
+            self.warnings.push(warning);
+        }
+    }
+}
+
+/// One content line: `NAME;PARAM=value:VALUE`.
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Join folded lines: a line starting with a space or tab continues the
+/// previous one.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if line.is_empty() => {}
+            _ => lines.push(String::from(line)),
+        }
+    }
+    lines
+}
+
+/// Split `s` at each `sep` outside double quotes.
+fn split_unquoted(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == sep && !quoted {
+            parts.push(&s[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    // The value starts at the first colon outside a quoted parameter
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        (c == ':' && !quoted).then_some(i)
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = split_unquoted(head, ';').into_iter();
+    let name = parts.next()?.to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.to_ascii_uppercase(), String::from(v.trim_matches('"'))))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: String::from(value),
+    })
+}
+
+/// Undo TEXT escaping (`\n`, `\,`, `\;`, `\\`).
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+fn digits(s: &str, range: core::ops::Range<usize>) -> Option<u32> {
+    let part = s.get(range)?;
+    if part.bytes().all(|b| b.is_ascii_digit()) {
+        part.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// A DATE or DATE-TIME value.
+struct DateTime {
+    utc_ms: u64,
+    /// Whether it was a whole date
+    is_date: bool,
+    /// Zone of a local time, if it named one
+    zone: Option<String>,
+}
+
+/// Parse `value` (`YYYYMMDD` or `YYYYMMDDTHHMMSS[Z]`), with the zone named
+/// by `tzid`. Unknown zones are read as UTC, with a warning.
+fn parse_date_time(
+    value: &str,
+    tzid: Option<&str>,
+    warnings: &mut Vec<String>,
+) -> Option<DateTime> {
+    let value = value.trim();
+    let year = digits(value, 0..4)? as i64;
+    let month = digits(value, 4..6)?;
+    let day = digits(value, 6..8)?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    if value.len() == 8 {
+        let utc_ms = u64::try_from(days * MS_PER_DAY).ok()?;
+        return Some(DateTime {
+            utc_ms,
+            is_date: true,
+            zone: None,
+        });
+    }
+    if value.as_bytes().get(8) != Some(&b'T') {
+        return None;
+    }
+    let (hour, minute, second) = (
+        digits(value, 9..11)?,
+        digits(value, 11..13)?,
+        digits(value, 13..15)?,
+    );
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let local_ms = days * MS_PER_DAY
+        + (hour as i64 * 3600 + minute as i64 * 60 + second.min(59) as i64) * 1000;
+    let utc = match (&value[15..], tzid) {
+        ("Z", _) | ("", None) => (local_ms, None),
+        ("", Some(name)) => match TimeZone::lookup(name) {
+            Some(zone) => (local_to_utc(&zone, local_ms), Some(zone.name)),
+            None => {
+                warnings.push(format!("Unknown timezone {}, read as UTC", name));
+                (local_ms, None)
+            }
+        },
+        _ => return None,
+    };
+    Some(DateTime {
+        utc_ms: u64::try_from(utc.0).ok()?,
+        is_date: false,
+        zone: utc.1,
+    })
+}
+
+/// Parse a DURATION (`[+-]P[nW][nD][T[nH][nM][nS]]`) in ms.
+fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (sign, rest) = match value.as_bytes().first()? {
+        b'-' => (-1, &value[1..]),
+        b'+' => (1, &value[1..]),
+        _ => (1, value),
+    };
+    let rest = rest.strip_prefix('P')?;
+    let mut total: i64 = 0;
+    let mut number: Option<i64> = None;
+    let mut in_time = false;
+    for c in rest.chars() {
+        if let Some(d) = c.to_digit(10) {
+            number = Some(number.unwrap_or(0).checked_mul(10)?.checked_add(d as i64)?);
+            continue;
+        }
+        let unit_ms = match (c, in_time) {
+            ('T', false) => {
+                in_time = true;
+                continue;
+            }
+            ('W', false) => 7 * MS_PER_DAY,
+            ('D', false) => MS_PER_DAY,
+            ('H', true) => 60 * MS_PER_MINUTE,
+            ('M', true) => MS_PER_MINUTE,
+            ('S', true) => 1000,
+            _ => return None,
+        };
+        total = total.checked_add(number.take()?.checked_mul(unit_ms)?)?;
+    }
+    if number.is_some() {
+        return None;
+    }
+    Some(sign * total)
+}
+
+/// Weekday number (0 = Sunday) of a BYDAY code.
+fn weekday_code(code: &str) -> Option<u32> {
+    ["SU", "MO", "TU", "WE", "TH", "FR", "SA"]
+        .iter()
+        .position(|&d| d == code)
+        .map(|d| d as u32)
+}
+
+/// Parse an RRULE, or say which part isn't supported.
+fn parse_rrule(value: &str, warnings: &mut Vec<String>) -> Result<Recurrence, String> {
+    let mut rule = Recurrence {
+        freq: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until_ms: None,
+        by_day: Vec::new(),
+    };
+    let mut freq = None;
+    for part in value.split(';').filter(|p| !p.is_empty()) {
+        let (key, val) = part.split_once('=').unwrap_or((part, ""));
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                freq = Some(match val.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    other => return Err(format!("FREQ={}", other)),
+                });
+            }
+            "INTERVAL" => rule.interval = val.parse().map_err(|_| format!("INTERVAL={}", val))?,
+            "COUNT" => rule.count = Some(val.parse().map_err(|_| format!("COUNT={}", val))?),
+            "UNTIL" => {
+                let until =
+                    parse_date_time(val, None, warnings).ok_or_else(|| format!("UNTIL={}", val))?;
+                // A date means the whole of that day
+                rule.until_ms = Some(if until.is_date {
+                    until.utc_ms + MS_PER_DAY as u64 - 1
+                } else {
+                    until.utc_ms
+                });
+            }
+            "BYDAY" => {
+                for code in val.split(',') {
+                    let day = weekday_code(&code.to_ascii_uppercase())
+                        .ok_or_else(|| format!("BYDAY={}", val))?;
+                    rule.by_day.push(day);
+                }
+            }
+            // Week start only matters for parts we don't support
+            "WKST" => {}
+            _ => return Err(String::from(key)),
+        }
+    }
+    rule.freq = freq.ok_or_else(|| String::from("missing FREQ"))?;
+    rule.validate()?;
+    Ok(rule)
+}
+
+/// The properties of one VEVENT, with its VALARMs' triggers.
+#[derive(Default)]
+struct RawEvent {
+    props: Vec<Property>,
+    triggers: Vec<Property>,
+}
+
+impl RawEvent {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.props.iter().find(|p| p.name == name)
+    }
+
+    fn text(&self, name: &str) -> String {
+        self.get(name)
+            .map(|p| unescape(&p.value))
+            .unwrap_or_default()
+    }
+}
+
+/// Short FNV-1a hash, for UIDs of events that have none.
+fn fnv1a(text: &str) -> u32 {
+    text.bytes().fold(0x811c_9dc5u32, |h, b| {
+        (h ^ b as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Convert one VEVENT, or say why it was skipped.
+fn convert(
+    raw: &RawEvent,
+    calendar: &str,
+    out: &mut ParsedCalendar,
+) -> Result<(String, EventFields), String> {
+    let title = raw.text("SUMMARY");
+    let title = if title.trim().is_empty() {
+        String::from("(untitled)")
+    } else {
+        title
+    };
+    if raw.get("RECURRENCE-ID").is_some() {
+        return Err(format!("{}: modified occurrences are not supported", title));
+    }
+    if raw
+        .get("STATUS")
+        .is_some_and(|s| s.value.eq_ignore_ascii_case("CANCELLED"))
+    {
+        return Err(format!("{}: cancelled", title));
+    }
+    let mut warnings = Vec::new();
+    let dtstart = raw
+        .get("DTSTART")
+        .ok_or_else(|| format!("{}: no DTSTART", title))?;
+    let start = parse_date_time(&dtstart.value, dtstart.param("TZID"), &mut warnings)
+        .ok_or_else(|| format!("{}: bad DTSTART {}", title, dtstart.value))?;
+
+    let end_ms = if let Some(dtend) = raw.get("DTEND") {
+        let end = parse_date_time(&dtend.value, dtend.param("TZID"), &mut warnings)
+            .ok_or_else(|| format!("{}: bad DTEND {}", title, dtend.value))?;
+        Some(end.utc_ms)
+    } else if let Some(duration) = raw.get("DURATION") {
+        let ms = parse_duration(&duration.value)
+            .filter(|&ms| ms >= 0)
+            .ok_or_else(|| format!("{}: bad DURATION {}", title, duration.value))?;
+        Some(start.utc_ms + ms as u64)
+    } else if start.is_date {
+        // An all-day event without an end lasts the day
+        Some(start.utc_ms + MS_PER_DAY as u64)
+    } else {
+        None
+    };
+
+    let recurrence = match raw.get("RRULE") {
+        Some(rrule) => match parse_rrule(&rrule.value, &mut warnings) {
+            Ok(rule) => Some(rule),
+            Err(part) => {
+                warnings.push(format!(
+                    "{}: unsupported RRULE ({}), imported the first occurrence only",
+                    title, part
+                ));
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut exdates_ms = Vec::new();
+    for exdate in raw.props.iter().filter(|p| p.name == "EXDATE") {
+        for value in exdate.value.split(',') {
+            match parse_date_time(value, exdate.param("TZID"), &mut warnings) {
+                // An excluded date removes the occurrence starting that day
+                Some(ex) if ex.is_date && !start.is_date => {
+                    exdates_ms.push(ex.utc_ms + start.utc_ms % MS_PER_DAY as u64)
+                }
+                Some(ex) => exdates_ms.push(ex.utc_ms),
+                None => warnings.push(format!("{}: bad EXDATE {}", title, value)),
+            }
+        }
+    }
+    if exdates_ms.len() > MAX_EXDATES {
+        warnings.push(format!("{}: kept the first {} EXDATEs", title, MAX_EXDATES));
+        exdates_ms.truncate(MAX_EXDATES);
+    }
+
+    let mut reminders_min = Vec::new();
+    for trigger in &raw.triggers {
+        let before = (trigger.param("VALUE").is_none()
+            && trigger.param("RELATED").is_none_or(|r| r == "START"))
+        .then(|| parse_duration(&trigger.value))
+        .flatten()
+        .filter(|&ms| ms <= 0);
+        match before {
+            Some(ms) => reminders_min.push((-ms / MS_PER_MINUTE) as u32),
+            None => warnings.push(format!(
+                "{}: unsupported alarm trigger {}",
+                title, trigger.value
+            )),
+        }
+    }
+    if reminders_min.len() > MAX_REMINDERS {
+        warnings.push(format!(
+            "{}: kept the first {} alarms",
+            title, MAX_REMINDERS
+        ));
+        reminders_min.truncate(MAX_REMINDERS);
+    }
+
+    let uid = raw
+        .get("UID")
+        .map(|p| p.value.trim())
+        .filter(|u| !u.is_empty());
+    let uid = match uid {
+        Some(uid) => String::from(uid),
+        // Stable, so importing the file again updates rather than duplicates
+        None => format!("ics-{}-{:08x}", start.utc_ms, fnv1a(&title)),
+    };
+    for warning in warnings {
+        out.warn(warning);
+    }
+    let fields = EventFields {
+        title,
+        description: raw.text("DESCRIPTION"),
+        location: raw.text("LOCATION"),
+        calendar: String::from(calendar),
+        start_ms: start.utc_ms,
+        end_ms,
+        all_day: start.is_date,
+        timezone: start.zone.unwrap_or_else(|| String::from("UTC")),
+        recurrence,
+        exdates_ms,
+        reminders_min,
+    };
+    Ok((uid, fields))
+}
+
+/// Read the events of `text` into `calendar`.
+pub fn parse(text: &str, calendar: &str) -> Result<ParsedCalendar, String> {
+    if text.len() > MAX_ICS_SIZE {
+        return Err(format!("Calendar too large (max {} bytes)", MAX_ICS_SIZE));
+    }
+    let lines = unfold(text);
+    if !lines
+        .first()
+        .is_some_and(|l| l.eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        return Err(String::from("Not an iCalendar file (no BEGIN:VCALENDAR)"));
+    }
+
+    let mut out = ParsedCalendar::default();
+    let mut event: Option<RawEvent> = None;
+    // Components nested in the event, innermost last
+    let mut nested: Vec<String> = Vec::new();
+    for line in &lines {
+        let Some(prop) = parse_property(line) else {
+            continue;
+        };
+        match (prop.name.as_str(), event.as_mut()) {
+            ("BEGIN", None) if prop.value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some(RawEvent::default());
+            }
+            ("BEGIN", Some(_)) => nested.push(prop.value.to_ascii_uppercase()),
+            ("END", Some(_)) if !nested.is_empty() => {
+                nested.pop();
+            }
+            ("END", Some(_)) => {
+                let raw = event.take().unwrap_or_default();
+                if out.events.len() >= MAX_EVENTS {
+                    out.skipped += 1;
+                    continue;
+                }
+                match convert(&raw, calendar, &mut out) {
+                    Ok(parsed) => out.events.push(parsed),
+                    Err(why) => {
+                        out.skipped += 1;
+                        out.warn(why);
+                    }
+                }
+            }
+            ("TRIGGER", Some(raw)) if nested.last().is_some_and(|c| c == "VALARM") => {
+                raw.triggers.push(prop);
+            }
+            (_, Some(raw)) if nested.is_empty() => raw.props.push(prop),
+            _ => {}
+        }
+    }
+    if out.events.len() >= MAX_EVENTS && out.skipped > 0 {
+        out.warn(format!("Imported the first {} events only", MAX_EVENTS));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3_600_000;
+    const DAY: u64 = 86_400_000;
+
+    fn date(year: i64, month: u32, day: u32) -> u64 {
+        days_from_civil(year, month, day) as u64 * DAY
+    }
+
+    const SAMPLE: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+SUMMARY:Team standup\\, daily\r\n\
+DESCRIPTION:Line one\\nline \r\n two\r\n\
+DTSTART;TZID=Europe/Berlin:20250106T090000\r\n\
+DURATION:PT15M\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR;UNTIL=20250131\r\n\
+EXDATE;TZID=Europe/Berlin:20250108T090000\r\n\
+BEGIN:VALARM\r\n\
+ACTION:DISPLAY\r\n\
+TRIGGER:-PT10M\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Holiday\r\n\
+DTSTART;VALUE=DATE:20251225\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:moved@example.com\r\n\
+RECURRENCE-ID:20250110T080000Z\r\n\
+DTSTART:20250110T100000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_sample() {
+        let parsed = parse(SAMPLE, "work").unwrap();
+        assert_eq!(parsed.events.len(), 2);
+        assert_eq!(parsed.skipped, 1);
+
+        let (uid, standup) = &parsed.events[0];
+        assert_eq!(uid, "standup@example.com");
+        assert_eq!(standup.title, "Team standup, daily");
+        assert_eq!(standup.description, "Line one\nline two");
+        assert_eq!(standup.calendar, "work");
+        assert_eq!(standup.timezone, "Europe/Berlin");
+        // 09:00 CET is 08:00 UTC
+        assert_eq!(standup.start_ms, date(2025, 1, 6) + 8 * HOUR);
+        assert_eq!(standup.end_ms, Some(standup.start_ms + HOUR / 4));
+        let rule = standup.recurrence.as_ref().unwrap();
+        assert_eq!(rule.by_day, alloc::vec![1, 3, 5]);
+        assert_eq!(rule.until_ms, Some(date(2025, 2, 1) - 1));
+        assert_eq!(standup.exdates_ms, alloc::vec![date(2025, 1, 8) + 8 * HOUR]);
+        assert_eq!(standup.reminders_min, alloc::vec![10]);
+
+        let (uid, holiday) = &parsed.events[1];
+        assert!(uid.starts_with("ics-"));
+        assert!(holiday.all_day);
+        assert_eq!(holiday.end_ms, Some(date(2025, 12, 26)));
+    }
+
+    #[test]
+    fn test_unsupported_parts_warn() {
+        let text = "BEGIN:VCALENDAR\n\
+BEGIN:VEVENT\n\
+UID:a\n\
+SUMMARY:Board meeting\n\
+DTSTART;TZID=Mars/Olympus:20250301T120000\n\
+RRULE:FREQ=MONTHLY;BYDAY=1MO\n\
+BEGIN:VALARM\n\
+TRIGGER;VALUE=DATE-TIME:20250301T110000Z\n\
+END:VALARM\n\
+END:VEVENT\n\
+END:VCALENDAR\n";
+        let parsed = parse(text, "default").unwrap();
+        assert_eq!(parsed.events.len(), 1);
+        let (_, event) = &parsed.events[0];
+        assert_eq!(event.timezone, "UTC");
+        assert!(event.recurrence.is_none());
+        assert!(event.reminders_min.is_empty());
+        assert_eq!(parsed.warnings.len(), 3);
+
+        assert!(parse("hello", "default").is_err());
+    }
+
+    #[test]
+    fn test_durations() {
+        assert_eq!(parse_duration("PT1H30M"), Some(90 * 60_000));
+        assert_eq!(parse_duration("-P1D"), Some(-(DAY as i64)));
+        assert_eq!(parse_duration("P1W"), Some(7 * DAY as i64));
+        assert_eq!(parse_duration("PT"), Some(0));
+        assert_eq!(parse_duration("P1H"), None);
+        assert_eq!(parse_duration("PT5"), None);
+    }
+}
@@ -0,0 +1,869 @@
+//! Calendar Service
+//!
+//! The CalendarService keeps the calendar events shared by the Calendar app
+//! and desktop widgets, so each of them doesn't keep its own copy. It:
+//! - Stores events via VFS (see [`store`])
+//! - Answers range queries with recurring events expanded (see [`recur`])
+//! - Keeps the soonest reminders armed as TimeService alarms, so they show
+//!   as notifications whether or not a calendar app is running (see
+//!   [`reminders`])
+//! - Imports iCalendar files read through VFS, or ICS text (see [`ics`])
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - ADD/UPDATE/DELETE: Event validated AND stored in memory AND a write of
+//!   the event store started AND reminders re-planned
+//! - QUERY: Occurrences computed from the stored events
+//! - IMPORT: Calendar parsed AND valid events merged by UID AND a write of
+//!   the event store started
+//!
+//! **Acceptable partial failure:**
+//! - Event store write fails → changes stay in memory until the service restarts
+//! - A reminder alarm is refused → that reminder is not shown
+//! - Events imported with parts left out, each reported as a warning
+//!
+//! **Forbidden:**
+//! - Changing events before the store has loaded (the write would replace it)
+//! - Unbounded event, occurrence or alarm growth (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_CAL_ADD (0x8700)`: Add an event
+//! - `MSG_CAL_UPDATE (0x8702)`: Replace an event's fields
+//! - `MSG_CAL_DELETE (0x8704)`: Delete an event
+//! - `MSG_CAL_QUERY (0x8706)`: Occurrences overlapping a time range
+//! - `MSG_CAL_IMPORT (0x8708)`: Import an ICS file (by VFS path) or ICS text
+//!
+//! # Reminders
+//!
+//! Alarms are set and cancelled with `MSG_TIME_SET_ALARM` and
+//! `MSG_TIME_CANCEL_ALARM`, sent to the TimeService by name. Its responses
+//! and `MSG_TIME_ALARM` come back through the supervisor, which routes the
+//! TimeService's replies to this service via Init. At startup the alarms
+//! left from a previous run are listed and cancelled before any are set.
+//!
+//! # Storage Access
+//!
+//! This service uses VFS IPC (async/await pattern, see
+//! [`zos_vfs::client::futures`]) to persist events and read calendars to
+//! import. All storage operations flow through the VFS service per Invariant 31.
+
+extern crate alloc;
+
+pub mod ics;
+pub mod recur;
+pub mod reminders;
+pub mod store;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+use zos_vfs::client::futures::VfsExecutor;
+
+use crate::manifests::CALENDAR_MANIFEST;
+use crate::response::JsonResponder;
+use crate::services::time::time_msg;
+use reminders::{alarm_label, ReminderSchedule, MAX_ARMED_REMINDERS};
+
+pub use store::{Event, EventFields, EventStore, Occurrence, CALENDAR_PATH, DEFAULT_CALENDAR};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for calendar service - re-exported from zos-ipc.
+pub mod calendar_msg {
+    pub use zos_ipc::calendar::*;
+}
+
+/// Event saves and calendar imports in flight at once (Rule 11)
+const MAX_VFS_TASKS: usize = 32;
+
+// =============================================================================
+// Request Types
+// =============================================================================
+
+/// Payload of MSG_CAL_UPDATE.
+#[derive(Clone, Debug, Deserialize)]
+struct UpdateRequest {
+    id: u32,
+    #[serde(flatten)]
+    fields: EventFields,
+}
+
+/// Payload of MSG_CAL_DELETE.
+#[derive(Clone, Debug, Deserialize)]
+struct DeleteRequest {
+    id: u32,
+}
+
+/// Payload of MSG_CAL_QUERY.
+#[derive(Clone, Debug, Deserialize)]
+struct QueryRequest {
+    from_ms: u64,
+    to_ms: u64,
+    #[serde(default)]
+    calendar: Option<String>,
+}
+
+/// Payload of MSG_CAL_QUERY_RESPONSE.
+#[derive(Clone, Debug, Serialize)]
+struct QueryResponse {
+    occurrences: Vec<Occurrence>,
+    truncated: bool,
+}
+
+/// Payload of MSG_CAL_IMPORT: a VFS path or the calendar text itself.
+#[derive(Clone, Debug, Deserialize)]
+struct ImportRequest {
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    ics: Option<String>,
+    #[serde(default)]
+    calendar: Option<String>,
+}
+
+/// Payload of MSG_CAL_IMPORT_RESPONSE.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+struct ImportResponse {
+    imported: u32,
+    updated: u32,
+    skipped: u32,
+    warnings: Vec<String>,
+}
+
+/// The alarms listed by MSG_TIME_LIST_ALARMS_RESPONSE; only IDs are needed.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct AlarmList {
+    #[serde(default)]
+    alarms: Vec<AlarmId>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct AlarmId {
+    id: u32,
+}
+
+// =============================================================================
+// VFS Tasks
+// =============================================================================
+
+/// A finished VFS task.
+enum Done {
+    /// The event store read on startup
+    Loaded(Result<Vec<u8>, String>),
+    /// A write of the event store after a change
+    Saved(Result<(), String>),
+    /// A calendar file read to import
+    ImportRead {
+        client_pid: u32,
+        cap_slots: Vec<u32>,
+        path: String,
+        calendar: String,
+        result: Result<Vec<u8>, String>,
+    },
+}
+
+// =============================================================================
+// CalendarService Application
+// =============================================================================
+
+/// CalendarService - shared calendar events and reminders
+pub struct CalendarService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// All events
+    store: EventStore,
+    /// Whether the event store has been loaded; until then it is neither
+    /// changed nor saved
+    loaded: bool,
+    /// Whether the alarms left from a previous run have been cancelled;
+    /// until then no reminders are armed
+    alarms_cleared: bool,
+    /// VFS reads and writes in progress
+    vfs: VfsExecutor<Done>,
+    /// Reminders armed as TimeService alarms
+    schedule: ReminderSchedule,
+}
+
+impl Default for CalendarService {
+    fn default() -> Self {
+        Self {
+            registered: false,
+            store: EventStore::default(),
+            loaded: false,
+            alarms_cleared: false,
+            vfs: VfsExecutor::new(),
+            schedule: ReminderSchedule::default(),
+        }
+    }
+}
+
+impl CalendarService {
+    /// Whether another save or import may start.
+    fn check_task_limit(&self) -> bool {
+        if self.vfs.running() >= MAX_VFS_TASKS {
+            syscall::debug(&format!(
+                "CalendarService: VFS task limit reached ({}/{})",
+                self.vfs.running(),
+                MAX_VFS_TASKS
+            ));
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Start a write of the event store and re-plan reminders after a change.
+    fn events_changed(&mut self, now_ms: u64) {
+        if self.check_task_limit() {
+            let value = self.store.to_json();
+            let vfs = self.vfs.handle();
+            self.vfs
+                .spawn(async move { Done::Saved(vfs.write(CALENDAR_PATH, &value).await) });
+            // Fails right away if the VFS can't be reached
+            self.finish_tasks(now_ms);
+        }
+        self.rearm(now_ms);
+    }
+
+    // =========================================================================
+    // Reminders
+    // =========================================================================
+
+    /// Arm the soonest reminders as TimeService alarms, cancelling those no
+    /// longer wanted.
+    fn rearm(&mut self, now_ms: u64) {
+        if !self.loaded || !self.alarms_cleared {
+            return;
+        }
+        let wanted = self.store.next_reminders(now_ms, MAX_ARMED_REMINDERS);
+        let plan = self.schedule.plan(wanted);
+        for id in plan.cancel {
+            self.cancel_alarm(id);
+        }
+        for reminder in plan.set {
+            let request = serde_json::json!({
+                "at_ms": reminder.fire_at_ms,
+                "label": alarm_label(&reminder),
+            });
+            let json = serde_json::to_vec(&request).unwrap_or_default();
+            if let Err(e) = syscall::send_named("time", time_msg::MSG_TIME_SET_ALARM, &json) {
+                syscall::debug(&format!(
+                    "CalendarService: Failed to set reminder for event {}: {}",
+                    reminder.event_id, e
+                ));
+                self.schedule.abandon(&reminder);
+            }
+        }
+    }
+
+    fn cancel_alarm(&self, id: u32) {
+        let json = format!(r#"{{"id":{}}}"#, id);
+        if let Err(e) =
+            syscall::send_named("time", time_msg::MSG_TIME_CANCEL_ALARM, json.as_bytes())
+        {
+            syscall::debug(&format!(
+                "CalendarService: Failed to cancel alarm {}: {}",
+                id, e
+            ));
+        }
+    }
+
+    /// Handle MSG_TIME_LIST_ALARMS_RESPONSE: cancel the alarms of a
+    /// previous run, then arm the reminders.
+    fn handle_alarm_list(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        if self.alarms_cleared {
+            return Ok(());
+        }
+        let list: AlarmList = serde_json::from_slice(&msg.data).unwrap_or_default();
+        for alarm in &list.alarms {
+            self.cancel_alarm(alarm.id);
+        }
+        syscall::debug(&format!(
+            "CalendarService: Cancelled {} alarms of a previous run",
+            list.alarms.len()
+        ));
+        self.alarms_cleared = true;
+        self.rearm(now_ms);
+        Ok(())
+    }
+
+    /// Handle MSG_TIME_SET_ALARM_RESPONSE
+    fn handle_alarm_set(&mut self, msg: &Message) -> Result<(), AppError> {
+        let alarm: Option<AlarmId> = serde_json::from_slice(&msg.data).ok();
+        if alarm.is_none() {
+            syscall::debug(&format!(
+                "CalendarService: Reminder alarm refused: {}",
+                String::from_utf8_lossy(&msg.data)
+            ));
+        }
+        if let Some(stale) = self.schedule.alarm_set(alarm.map(|a| a.id)) {
+            self.cancel_alarm(stale);
+        }
+        Ok(())
+    }
+
+    /// Handle MSG_TIME_ALARM: a reminder was shown, arm the next one.
+    fn handle_alarm_fired(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        if let Ok(alarm) = serde_json::from_slice::<AlarmId>(&msg.data) {
+            if self.schedule.fired(alarm.id) {
+                self.rearm(now_ms);
+            }
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Refuse changes until the store has loaded. Returns true if refused.
+    fn refuse_until_loaded(&self, msg: &Message, tag: u32) -> Result<bool, AppError> {
+        if self.loaded {
+            return Ok(false);
+        }
+        self.send_error_response(
+            msg.from_pid,
+            &msg.cap_slots,
+            tag,
+            "Service busy: calendar is loading",
+        )?;
+        Ok(true)
+    }
+
+    /// Handle MSG_CAL_ADD
+    fn handle_add(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let tag = calendar_msg::MSG_CAL_ADD_RESPONSE;
+        if self.refuse_until_loaded(msg, tag)? {
+            return Ok(());
+        }
+        let fields: EventFields = match serde_json::from_slice(&msg.data) {
+            Ok(f) => f,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid event: expected {\"title\": string, \"start_ms\": u64}",
+                );
+            }
+        };
+
+        match self.store.add(fields, None) {
+            Ok(event) => {
+                syscall::debug(&format!(
+                    "CalendarService: PID {} added event {}",
+                    msg.from_pid, event.id
+                ));
+                self.events_changed(now_ms);
+                self.send_event_response(msg, tag, &event)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_CAL_UPDATE
+    fn handle_update(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let tag = calendar_msg::MSG_CAL_UPDATE_RESPONSE;
+        if self.refuse_until_loaded(msg, tag)? {
+            return Ok(());
+        }
+        let request: UpdateRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid update: expected {\"id\": u32, \"title\": string, \"start_ms\": u64}",
+                );
+            }
+        };
+
+        match self.store.update(request.id, request.fields) {
+            Ok(event) => {
+                self.events_changed(now_ms);
+                self.send_event_response(msg, tag, &event)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_CAL_DELETE
+    fn handle_delete(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let tag = calendar_msg::MSG_CAL_DELETE_RESPONSE;
+        if self.refuse_until_loaded(msg, tag)? {
+            return Ok(());
+        }
+        let request: DeleteRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid delete: expected {\"id\": u32}",
+                );
+            }
+        };
+
+        match self.store.delete(request.id) {
+            Ok(event) => {
+                self.events_changed(now_ms);
+                self.send_event_response(msg, tag, &event)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_CAL_QUERY
+    fn handle_query(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = calendar_msg::MSG_CAL_QUERY_RESPONSE;
+        let request: QueryRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid query: expected {\"from_ms\": u64, \"to_ms\": u64}",
+                );
+            }
+        };
+        if request.to_ms < request.from_ms {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Invalid query: range ends before it starts",
+            );
+        }
+
+        let calendar = request.calendar.as_deref();
+        let (occurrences, truncated) = self.store.query(request.from_ms, request.to_ms, calendar);
+        let json = serde_json::to_vec(&QueryResponse {
+            occurrences,
+            truncated,
+        })
+        .unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_CAL_IMPORT
+    fn handle_import(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let tag = calendar_msg::MSG_CAL_IMPORT_RESPONSE;
+        if self.refuse_until_loaded(msg, tag)? {
+            return Ok(());
+        }
+        let request: ImportRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid import: expected {\"path\": string} or {\"ics\": string}",
+                );
+            }
+        };
+        let calendar = request
+            .calendar
+            .unwrap_or_else(|| String::from(DEFAULT_CALENDAR));
+
+        match (request.path, request.ics) {
+            (None, Some(text)) => {
+                let result = self.import(&text, &calendar, now_ms);
+                self.send_import_response(msg.from_pid, &msg.cap_slots, result)
+            }
+            (Some(path), None) => {
+                if !self.check_task_limit() {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Service busy: VFS task limit reached",
+                    );
+                }
+                syscall::debug(&format!(
+                    "CalendarService: PID {} imports {} into {}",
+                    msg.from_pid, path, calendar
+                ));
+                let vfs = self.vfs.handle();
+                let client_pid = msg.from_pid;
+                let cap_slots = msg.cap_slots.clone();
+                self.vfs.spawn(async move {
+                    let result = vfs.read(&path).await;
+                    Done::ImportRead {
+                        client_pid,
+                        cap_slots,
+                        path,
+                        calendar,
+                        result,
+                    }
+                });
+                self.finish_tasks(now_ms);
+                Ok(())
+            }
+            _ => self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Invalid import: give exactly one of \"path\" and \"ics\"",
+            ),
+        }
+    }
+
+    /// Merge the events of `text` into `calendar`.
+    fn import(
+        &mut self,
+        text: &str,
+        calendar: &str,
+        now_ms: u64,
+    ) -> Result<ImportResponse, String> {
+        let parsed = ics::parse(text, calendar)?;
+        let mut warnings = parsed.warnings;
+        let (counts, refused) = self.store.merge(parsed.events);
+        let skipped = parsed.skipped + refused.len() as u32;
+        warnings.extend(refused);
+        syscall::debug(&format!(
+            "CalendarService: Imported {} events, updated {}, skipped {}",
+            counts.imported, counts.updated, skipped
+        ));
+        if counts.imported + counts.updated > 0 {
+            self.events_changed(now_ms);
+        }
+        Ok(ImportResponse {
+            imported: counts.imported,
+            updated: counts.updated,
+            skipped,
+            warnings,
+        })
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Handle a VFS response to one of our tasks. Returns false if the
+    /// message isn't one.
+    fn handle_vfs_response(&mut self, msg: &Message, now_ms: u64) -> bool {
+        if !self.vfs.on_message(msg.tag, &msg.data) {
+            return false;
+        }
+        self.finish_tasks(now_ms);
+        true
+    }
+
+    /// Handle the outcome of every finished VFS task.
+    fn finish_tasks(&mut self, now_ms: u64) {
+        while let Some(done) = self.vfs.next_finished() {
+            match done {
+                Done::Loaded(result) => {
+                    match result.ok().and_then(|data| EventStore::from_json(&data)) {
+                        Some(stored) => {
+                            syscall::debug(&format!(
+                                "CalendarService: Loaded {} events",
+                                stored.len()
+                            ));
+                            self.store = stored;
+                        }
+                        None => syscall::debug("CalendarService: No stored events found"),
+                    }
+                    self.loaded = true;
+                    self.rearm(now_ms);
+                }
+
+                Done::Saved(result) => {
+                    if let Err(e) = result {
+                        syscall::debug(&format!(
+                            "CalendarService: Failed to write {}: {}",
+                            CALENDAR_PATH, e
+                        ));
+                    }
+                }
+
+                Done::ImportRead {
+                    client_pid,
+                    cap_slots,
+                    path,
+                    calendar,
+                    result,
+                } => {
+                    let result = match result {
+                        Ok(data) => match String::from_utf8(data) {
+                            Ok(text) => self.import(&text, &calendar, now_ms),
+                            Err(_) => Err(format!("{} is not UTF-8 text", path)),
+                        },
+                        // Rule 9: Include operation context in error
+                        Err(e) => Err(format!("VFS read failed for {}: {}", path, e)),
+                    };
+                    if let Err(e) = self.send_import_response(client_pid, &cap_slots, result) {
+                        syscall::debug(&format!(
+                            "CalendarService: Failed to answer import from PID {}: {:?}",
+                            client_pid, e
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // =========================================================================
+    // Response helpers
+    // =========================================================================
+
+    fn send_event_response(&self, msg: &Message, tag: u32, event: &Event) -> Result<(), AppError> {
+        let json = serde_json::to_vec(event).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    fn send_import_response(
+        &self,
+        to_pid: u32,
+        cap_slots: &[u32],
+        result: Result<ImportResponse, String>,
+    ) -> Result<(), AppError> {
+        let tag = calendar_msg::MSG_CAL_IMPORT_RESPONSE;
+        match result {
+            Ok(response) => {
+                let json = serde_json::to_vec(&response).unwrap_or_default();
+                self.send_response(to_pid, cap_slots, tag, &json)
+            }
+            Err(e) => self.send_error_response(to_pid, cap_slots, tag, &e),
+        }
+    }
+}
+
+impl JsonResponder for CalendarService {
+    const SERVICE_NAME: &'static str = "CalendarService";
+}
+
+impl ZeroApp for CalendarService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &CALENDAR_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("CalendarService starting (PID {})", ctx.pid));
+
+        // Register with init as "calendar" service
+        let service_name = "calendar";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("CalendarService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+
+        // Load events via VFS, and clear the alarms of a previous run
+        let vfs = self.vfs.handle();
+        self.vfs
+            .spawn(async move { Done::Loaded(vfs.read(CALENDAR_PATH).await) });
+        self.finish_tasks(ctx.wallclock_ms);
+        if let Err(e) = syscall::send_named("time", time_msg::MSG_TIME_LIST_ALARMS, &[]) {
+            syscall::debug(&format!("CalendarService: Failed to list alarms: {}", e));
+        }
+
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        let now_ms = ctx.wallclock_ms;
+        // VFS responses (Invariant 31 compliant - storage via VFS IPC)
+        if self.handle_vfs_response(&msg, now_ms) {
+            return Ok(());
+        }
+
+        match msg.tag {
+            // TimeService replies and fired reminders
+            time_msg::MSG_TIME_LIST_ALARMS_RESPONSE => self.handle_alarm_list(&msg, now_ms),
+            time_msg::MSG_TIME_SET_ALARM_RESPONSE => self.handle_alarm_set(&msg),
+            time_msg::MSG_TIME_CANCEL_ALARM_RESPONSE => Ok(()),
+            time_msg::MSG_TIME_ALARM => self.handle_alarm_fired(&msg, now_ms),
+
+            // Calendar service protocol
+            calendar_msg::MSG_CAL_ADD => self.handle_add(&msg, now_ms),
+            calendar_msg::MSG_CAL_UPDATE => self.handle_update(&msg, now_ms),
+            calendar_msg::MSG_CAL_DELETE => self.handle_delete(&msg, now_ms),
+            calendar_msg::MSG_CAL_QUERY => self.handle_query(&msg),
+            calendar_msg::MSG_CAL_IMPORT => self.handle_import(&msg, now_ms),
+
+            _ => {
+                syscall::debug(&format!(
+                    "CalendarService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("CalendarService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+
+    const HOUR: u64 = 3_600_000;
+
+    fn loaded() -> CalendarService {
+        CalendarService {
+            loaded: true,
+            ..Default::default()
+        }
+    }
+
+    fn add(service: &mut CalendarService, json: &str) {
+        let msg = mock_message(calendar_msg::MSG_CAL_ADD, 7, json.as_bytes().to_vec());
+        service.handle_add(&msg, 0).unwrap();
+    }
+
+    #[test]
+    fn test_changes_refused_until_loaded() {
+        let mut service = CalendarService::default();
+        add(&mut service, r#"{"title":"Early","start_ms":3600000}"#);
+        assert!(service.store.is_empty());
+        assert_eq!(service.vfs.running(), 0);
+
+        let mut service = loaded();
+        add(&mut service, r#"{"title":"Early","start_ms":3600000}"#);
+        assert_eq!(service.store.len(), 1);
+        // A write of the store was started
+        assert_eq!(service.vfs.running(), 1);
+    }
+
+    #[test]
+    fn test_update_and_delete_requests() {
+        let mut service = loaded();
+        add(&mut service, r#"{"title":"Dentist","start_ms":3600000}"#);
+        let id = service.store.query(0, 2 * HOUR, None).0[0].event_id;
+
+        let json = format!(r#"{{"id":{},"title":"Dentist","start_ms":7200000}}"#, id);
+        let msg = mock_message(calendar_msg::MSG_CAL_UPDATE, 7, json.into_bytes());
+        service.handle_update(&msg, 0).unwrap();
+        let (found, _) = service.store.query(0, 3 * HOUR, None);
+        assert_eq!(found[0].start_ms, 2 * HOUR);
+
+        let json = format!(r#"{{"id":{}}}"#, id);
+        let msg = mock_message(calendar_msg::MSG_CAL_DELETE, 7, json.into_bytes());
+        service.handle_delete(&msg, 0).unwrap();
+        assert!(service.store.is_empty());
+    }
+
+    #[test]
+    fn test_import_text_merges_by_uid() {
+        let mut service = loaded();
+        let ics = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:x@example\nSUMMARY:Launch\n\
+DTSTART:20250301T120000Z\nEND:VEVENT\nEND:VCALENDAR\n";
+        let first = service.import(ics, "work", 0).unwrap();
+        assert_eq!((first.imported, first.updated, first.skipped), (1, 0, 0));
+        let again = service.import(ics, "work", 0).unwrap();
+        assert_eq!((again.imported, again.updated), (0, 1));
+        assert_eq!(service.store.len(), 1);
+        assert!(service.import("not a calendar", "work", 0).is_err());
+    }
+
+    #[test]
+    fn test_import_needs_one_source() {
+        let mut service = loaded();
+        let msg = mock_message(
+            calendar_msg::MSG_CAL_IMPORT,
+            7,
+            br#"{"path":"/home/a.ics","ics":"BEGIN:VCALENDAR"}"#.to_vec(),
+        );
+        service.handle_import(&msg, 0).unwrap();
+        assert_eq!(service.vfs.running(), 0);
+
+        let msg = mock_message(
+            calendar_msg::MSG_CAL_IMPORT,
+            7,
+            br#"{"path":"/home/a.ics"}"#.to_vec(),
+        );
+        service.handle_import(&msg, 0).unwrap();
+        assert_eq!(service.vfs.running(), 1);
+    }
+
+    #[test]
+    fn test_import_from_file() {
+        let mut service = loaded();
+        let msg = mock_message(
+            calendar_msg::MSG_CAL_IMPORT,
+            7,
+            br#"{"path":"/home/a.ics","calendar":"work"}"#.to_vec(),
+        );
+        service.handle_import(&msg, 0).unwrap();
+
+        let ics = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:x@example\nSUMMARY:Launch\n\
+DTSTART:20250301T120000Z\nEND:VEVENT\nEND:VCALENDAR\n";
+        let content = serde_json::json!({ "result": { "Ok": ics.as_bytes() } });
+        let read = mock_message(
+            zos_vfs::ipc::vfs_msg::MSG_VFS_READ_RESPONSE,
+            4,
+            serde_json::to_vec(&content).unwrap(),
+        );
+        assert!(service.handle_vfs_response(&read, 0));
+        assert_eq!(service.store.len(), 1);
+        // The import read finished and a write of the store started
+        assert_eq!(service.vfs.running(), 1);
+    }
+
+    #[test]
+    fn test_reminders_armed_after_old_alarms_cleared() {
+        let mut service = loaded();
+        add(
+            &mut service,
+            r#"{"title":"Call","start_ms":7200000,"reminders_min":[15]}"#,
+        );
+        // Nothing is armed before the previous run's alarms are cancelled
+        let msg = mock_message(
+            time_msg::MSG_TIME_SET_ALARM_RESPONSE,
+            6,
+            br#"{"id":40}"#.to_vec(),
+        );
+        service.handle_alarm_set(&msg).unwrap();
+        assert_eq!(service.schedule.armed_len(), 0);
+
+        let list = mock_message(
+            time_msg::MSG_TIME_LIST_ALARMS_RESPONSE,
+            6,
+            br#"{"alarms":[{"id":3,"owner":"calendar"}]}"#.to_vec(),
+        );
+        service.handle_alarm_list(&list, 0).unwrap();
+        assert!(service.alarms_cleared);
+        service.handle_alarm_set(&msg).unwrap();
+        assert_eq!(service.schedule.armed_len(), 1);
+
+        let fired = mock_message(time_msg::MSG_TIME_ALARM, 6, br#"{"id":40}"#.to_vec());
+        service.handle_alarm_fired(&fired, 2 * HOUR).unwrap();
+        assert_eq!(service.schedule.armed_len(), 0);
+    }
+}
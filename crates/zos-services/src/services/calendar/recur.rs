@@ -0,0 +1,312 @@
+//! Recurrence rules
+//!
+//! A subset of RFC 5545 `RRULE`: daily, weekly (on one or more weekdays),
+//! monthly on the day of the month of the first occurrence and yearly on
+//! its date, every `interval` periods, with an optional count or end.
+//! Occurrences keep the wall-clock time of the first one in the event's
+//! zone across daylight saving changes. As in RFC 5545, months without the
+//! day (the 31st, February 29th) are skipped rather than clamped.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::services::time::tz::{civil_from_days, days_from_civil, weekday, TimeZone};
+
+/// Milliseconds per minute.
+pub const MS_PER_MINUTE: i64 = 60_000;
+
+/// Milliseconds per day.
+pub const MS_PER_DAY: i64 = 86_400_000;
+
+/// Largest interval accepted.
+const MAX_INTERVAL: u32 = 1000;
+
+/// Most candidate periods examined per expansion (Rule 11: resource limits)
+const MAX_STEPS: usize = 20_000;
+
+/// How often an event repeats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+/// When a recurring event repeats.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub freq: Frequency,
+    /// Repeat every this many periods
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    /// Number of occurrences, the first included
+    #[serde(default)]
+    pub count: Option<u32>,
+    /// Last time an occurrence may start, ms since the Unix epoch
+    #[serde(default)]
+    pub until_ms: Option<u64>,
+    /// Weekdays of a weekly rule (0 = Sunday); the first occurrence's if empty
+    #[serde(default)]
+    pub by_day: Vec<u32>,
+}
+
+impl Recurrence {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval == 0 || self.interval > MAX_INTERVAL {
+            return Err(format!("Interval must be 1 to {}", MAX_INTERVAL));
+        }
+        if self.count == Some(0) {
+            return Err(String::from("Count must be at least 1"));
+        }
+        if self.by_day.iter().any(|&d| d > 6) {
+            return Err(String::from("Weekdays must be 0 (Sunday) to 6"));
+        }
+        if !self.by_day.is_empty() && self.freq != Frequency::Weekly {
+            return Err(String::from("Weekdays are only supported for weekly rules"));
+        }
+        Ok(())
+    }
+}
+
+/// Wall-clock time in `zone` of `utc_ms`.
+pub fn utc_to_local(zone: &TimeZone, utc_ms: i64) -> i64 {
+    utc_ms + zone.offset_at(utc_ms).minutes as i64 * MS_PER_MINUTE
+}
+
+/// UTC time of wall-clock time `local_ms` in `zone`, using the offset in
+/// effect around that time.
+pub fn local_to_utc(zone: &TimeZone, local_ms: i64) -> i64 {
+    let guess = local_ms - zone.offset_at(local_ms).minutes as i64 * MS_PER_MINUTE;
+    local_ms - zone.offset_at(guess).minutes as i64 * MS_PER_MINUTE
+}
+
+/// Days since the epoch of a date, if the month has that day.
+fn valid_day(year: i64, month: u32, day: u32) -> Option<i64> {
+    let days = days_from_civil(year, month, day);
+    (civil_from_days(days) == (year, month, day)).then_some(days)
+}
+
+/// Start times of the occurrences of a series first starting at `start_ms`
+/// that start in `[from_ms, to_ms)`, soonest first and at most `limit`.
+pub fn occurrences(
+    start_ms: u64,
+    rule: Option<&Recurrence>,
+    zone: &TimeZone,
+    from_ms: u64,
+    to_ms: u64,
+    limit: usize,
+) -> Vec<u64> {
+    let Some(rule) = rule else {
+        return if (from_ms..to_ms).contains(&start_ms) && limit > 0 {
+            vec![start_ms]
+        } else {
+            Vec::new()
+        };
+    };
+
+    let local0 = utc_to_local(zone, start_ms as i64);
+    let day0 = local0.div_euclid(MS_PER_DAY);
+    let time_of_day = local0.rem_euclid(MS_PER_DAY);
+    let (year0, month0, date0) = civil_from_days(day0);
+    let interval = rule.interval.max(1) as i64;
+    let mut weekdays: Vec<i64> = if rule.by_day.is_empty() {
+        vec![weekday(day0) as i64]
+    } else {
+        rule.by_day.iter().map(|&d| d as i64).collect()
+    };
+    weekdays.sort_unstable();
+    weekdays.dedup();
+
+    // Without a count nothing before the range matters: skip to just
+    // before it (a period early, for daylight saving slack)
+    let mut period: i64 = 0;
+    let period_days = match rule.freq {
+        Frequency::Daily => interval,
+        Frequency::Weekly => 7 * interval,
+        Frequency::Monthly | Frequency::Yearly => 0,
+    };
+    if rule.count.is_none() && period_days > 0 && from_ms > start_ms {
+        period = ((from_ms - start_ms) as i64 / (period_days * MS_PER_DAY) - 1).max(0);
+    }
+
+    let mut found = Vec::new();
+    let mut seen: u32 = 0;
+    for _ in 0..MAX_STEPS {
+        let days: Vec<i64> = match rule.freq {
+            Frequency::Daily => vec![day0 + period * interval],
+            Frequency::Weekly => {
+                let week = day0 - weekday(day0) as i64 + period * 7 * interval;
+                weekdays
+                    .iter()
+                    .map(|d| week + d)
+                    .filter(|&d| d >= day0)
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months = month0 as i64 - 1 + period * interval;
+                let month = months.rem_euclid(12) as u32 + 1;
+                valid_day(year0 + months.div_euclid(12), month, date0)
+                    .into_iter()
+                    .collect()
+            }
+            Frequency::Yearly => valid_day(year0 + period * interval, month0, date0)
+                .into_iter()
+                .collect(),
+        };
+        period += 1;
+
+        for day in days {
+            let utc = local_to_utc(zone, day * MS_PER_DAY + time_of_day);
+            if utc < 0 {
+                continue;
+            }
+            let utc = utc as u64;
+            if utc >= to_ms || rule.until_ms.is_some_and(|until| utc > until) {
+                return found;
+            }
+            seen += 1;
+            if rule.count.is_some_and(|count| seen > count) {
+                return found;
+            }
+            if utc >= from_ms {
+                found.push(utc);
+                if found.len() >= limit {
+                    return found;
+                }
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3_600_000;
+    const DAY: u64 = 86_400_000;
+
+    fn date(year: i64, month: u32, day: u32, hour: u64) -> u64 {
+        days_from_civil(year, month, day) as u64 * DAY + hour * HOUR
+    }
+
+    fn rule(freq: Frequency) -> Recurrence {
+        Recurrence {
+            freq,
+            interval: 1,
+            count: None,
+            until_ms: None,
+            by_day: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_single_event() {
+        let utc = TimeZone::utc();
+        let start = date(2025, 3, 1, 9);
+        assert_eq!(
+            occurrences(start, None, &utc, start, start + 1, 10),
+            vec![start]
+        );
+        assert!(occurrences(start, None, &utc, start + 1, start + DAY, 10).is_empty());
+    }
+
+    #[test]
+    fn test_weekly_keeps_local_time_across_dst() {
+        // Mondays 09:00 in New York, from 2025-03-03 (EST) past the 9 March switch
+        let zone = TimeZone::lookup("America/New_York").unwrap();
+        let start = date(2025, 3, 3, 14);
+        let weekly = rule(Frequency::Weekly);
+        let found = occurrences(start, Some(&weekly), &zone, start, start + 13 * DAY, 10);
+        assert_eq!(found, vec![start, date(2025, 3, 10, 13)]);
+    }
+
+    #[test]
+    fn test_weekly_by_day_count_and_until() {
+        let utc = TimeZone::utc();
+        // Wednesday 2025-01-01
+        let start = date(2025, 1, 1, 8);
+        let mut mon_wed = rule(Frequency::Weekly);
+        mon_wed.by_day = vec![1, 3];
+        mon_wed.count = Some(4);
+        let found = occurrences(start, Some(&mon_wed), &utc, 0, u64::MAX, 10);
+        assert_eq!(
+            found,
+            vec![
+                start,
+                date(2025, 1, 6, 8),
+                date(2025, 1, 8, 8),
+                date(2025, 1, 13, 8)
+            ]
+        );
+
+        // The count runs from the first occurrence, not the range
+        let later = occurrences(
+            start,
+            Some(&mon_wed),
+            &utc,
+            date(2025, 1, 7, 0),
+            u64::MAX,
+            10,
+        );
+        assert_eq!(later, vec![date(2025, 1, 8, 8), date(2025, 1, 13, 8)]);
+
+        let mut daily = rule(Frequency::Daily);
+        daily.until_ms = Some(date(2025, 1, 3, 8));
+        assert_eq!(
+            occurrences(start, Some(&daily), &utc, 0, u64::MAX, 10).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_monthly_and_yearly_skip_missing_days() {
+        let utc = TimeZone::utc();
+        let start = date(2025, 1, 31, 12);
+        let monthly = rule(Frequency::Monthly);
+        let found = occurrences(start, Some(&monthly), &utc, 0, date(2025, 6, 1, 0), 10);
+        assert_eq!(
+            found,
+            vec![start, date(2025, 3, 31, 12), date(2025, 5, 31, 12)]
+        );
+
+        let leap = date(2024, 2, 29, 0);
+        let mut yearly = rule(Frequency::Yearly);
+        yearly.interval = 1;
+        let found = occurrences(leap, Some(&yearly), &utc, 0, date(2030, 1, 1, 0), 10);
+        assert_eq!(found, vec![leap, date(2028, 2, 29, 0)]);
+    }
+
+    #[test]
+    fn test_long_running_series_skip_ahead() {
+        let utc = TimeZone::utc();
+        let start = date(1990, 1, 1, 7);
+        let daily = rule(Frequency::Daily);
+        let from = date(2025, 6, 1, 0);
+        let found = occurrences(start, Some(&daily), &utc, from, from + 3 * DAY, 10);
+        assert_eq!(
+            found,
+            vec![
+                from + 7 * HOUR,
+                from + DAY + 7 * HOUR,
+                from + 2 * DAY + 7 * HOUR
+            ]
+        );
+
+        let mut bad = rule(Frequency::Monthly);
+        bad.by_day = vec![1];
+        assert!(bad.validate().is_err());
+        bad = rule(Frequency::Daily);
+        bad.interval = 0;
+        assert!(bad.validate().is_err());
+    }
+}
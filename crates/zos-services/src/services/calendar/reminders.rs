@@ -0,0 +1,170 @@
+//! Event reminders as TimeService alarms
+//!
+//! The CalendarService keeps the soonest `MAX_ARMED_REMINDERS` reminders
+//! armed as alarms in the TimeService, which shows them as notifications
+//! at the right time whether or not a calendar app is running. When an
+//! alarm fires, or events change, the schedule is planned again: alarms no
+//! longer wanted are cancelled and the new soonest reminders set.
+//!
+//! Alarms are set with MSG_TIME_SET_ALARM and the TimeService answers in
+//! order, so each response is matched to the oldest request still
+//! awaiting one.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::store::Reminder;
+
+/// Most reminders armed at once; the TimeService allows an app 16 alarms
+pub const MAX_ARMED_REMINDERS: usize = 8;
+
+/// Maximum alarm label length in bytes, as the TimeService accepts
+const MAX_LABEL_LEN: usize = 128;
+
+/// Label of `reminder`, cut to what the TimeService accepts.
+pub fn alarm_label(reminder: &Reminder) -> String {
+    let mut end = reminder.label.len().min(MAX_LABEL_LEN);
+    while !reminder.label.is_char_boundary(end) {
+        end -= 1;
+    }
+    String::from(&reminder.label[..end])
+}
+
+/// What to change to arm the wanted reminders.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Plan {
+    /// Alarms to cancel
+    pub cancel: Vec<u32>,
+    /// Reminders to set alarms for
+    pub set: Vec<Reminder>,
+}
+
+/// Reminders armed as alarms, or being set.
+#[derive(Debug, Default)]
+pub struct ReminderSchedule {
+    /// Armed reminders by alarm ID
+    armed: BTreeMap<u32, Reminder>,
+    /// Reminders whose MSG_TIME_SET_ALARM awaits a response, oldest first
+    requested: VecDeque<Reminder>,
+    /// The reminders that should be armed
+    wanted: Vec<Reminder>,
+}
+
+impl ReminderSchedule {
+    /// Plan the changes that arm `wanted` instead of what is armed now.
+    /// The reminders to set are taken as requested.
+    pub fn plan(&mut self, wanted: Vec<Reminder>) -> Plan {
+        let cancel: Vec<u32> = self
+            .armed
+            .iter()
+            .filter(|(_, r)| !wanted.contains(r))
+            .map(|(&id, _)| id)
+            .collect();
+        for id in &cancel {
+            self.armed.remove(id);
+        }
+        let set: Vec<Reminder> = wanted
+            .iter()
+            .filter(|r| !self.armed.values().any(|a| a == *r) && !self.requested.contains(r))
+            .cloned()
+            .collect();
+        self.requested.extend(set.iter().cloned());
+        self.wanted = wanted;
+        Plan { cancel, set }
+    }
+
+    /// Record the response to the oldest MSG_TIME_SET_ALARM: the alarm
+    /// ID, or `None` if it was refused. Returns an alarm to cancel if the
+    /// reminder stopped being wanted while it was being set.
+    pub fn alarm_set(&mut self, alarm_id: Option<u32>) -> Option<u32> {
+        let reminder = self.requested.pop_front()?;
+        let id = alarm_id?;
+        if self.wanted.contains(&reminder) {
+            self.armed.insert(id, reminder);
+            None
+        } else {
+            Some(id)
+        }
+    }
+
+    /// Forget a requested reminder whose MSG_TIME_SET_ALARM wasn't sent.
+    pub fn abandon(&mut self, reminder: &Reminder) {
+        if let Some(i) = self.requested.iter().rposition(|r| r == reminder) {
+            self.requested.remove(i);
+        }
+    }
+
+    /// Forget the alarm that fired. Returns whether it was a reminder.
+    pub fn fired(&mut self, alarm_id: u32) -> bool {
+        self.armed.remove(&alarm_id).is_some()
+    }
+
+    /// Number of armed reminders.
+    pub fn armed_len(&self) -> usize {
+        self.armed.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reminder(fire_at_ms: u64, label: &str) -> Reminder {
+        Reminder {
+            fire_at_ms,
+            event_id: 1,
+            start_ms: fire_at_ms + 600_000,
+            label: String::from(label),
+        }
+    }
+
+    #[test]
+    fn test_plan_sets_and_cancels() {
+        let mut schedule = ReminderSchedule::default();
+        let (a, b, c) = (reminder(1, "a"), reminder(2, "b"), reminder(3, "c"));
+
+        let plan = schedule.plan(alloc::vec![a.clone(), b.clone()]);
+        assert!(plan.cancel.is_empty());
+        assert_eq!(plan.set, alloc::vec![a.clone(), b.clone()]);
+        // Planning again while the alarms are being set sets nothing twice
+        assert!(schedule
+            .plan(alloc::vec![a.clone(), b.clone()])
+            .set
+            .is_empty());
+
+        assert_eq!(schedule.alarm_set(Some(10)), None);
+        assert_eq!(schedule.alarm_set(Some(11)), None);
+        assert_eq!(schedule.armed_len(), 2);
+
+        let plan = schedule.plan(alloc::vec![b, c.clone()]);
+        assert_eq!(plan.cancel, alloc::vec![10]);
+        assert_eq!(plan.set, alloc::vec![c]);
+
+        assert!(schedule.fired(11));
+        assert!(!schedule.fired(11));
+    }
+
+    #[test]
+    fn test_unwanted_while_setting_is_cancelled() {
+        let mut schedule = ReminderSchedule::default();
+        schedule.plan(alloc::vec![reminder(1, "a"), reminder(2, "b")]);
+        schedule.plan(alloc::vec![reminder(2, "b")]);
+        assert_eq!(schedule.alarm_set(Some(10)), Some(10));
+        assert_eq!(schedule.alarm_set(None), None);
+        assert_eq!(schedule.armed_len(), 0);
+        assert_eq!(schedule.alarm_set(Some(12)), None);
+
+        let plan = schedule.plan(alloc::vec![reminder(2, "b")]);
+        schedule.abandon(&plan.set[0]);
+        assert_eq!(schedule.alarm_set(Some(13)), None);
+        assert_eq!(schedule.armed_len(), 0);
+    }
+
+    #[test]
+    fn test_label_is_cut_on_char_boundary() {
+        let long = "é".repeat(100);
+        let label = alarm_label(&reminder(1, &long));
+        assert_eq!(label.len(), 128);
+    }
+}
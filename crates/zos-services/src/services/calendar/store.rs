@@ -0,0 +1,510 @@
+//! Calendar event store
+//!
+//! Events live in `/system/settings/calendar.json`, shared by every app:
+//! the Calendar app edits them and desktop widgets query them. Times are ms
+//! since the Unix epoch; a recurring event repeats in its own zone, and an
+//! all-day event spans whole UTC days, so it falls on the same date
+//! wherever it is viewed.
+//!
+//! Queries expand recurring events into the occurrences overlapping the
+//! range, minus any excluded dates. Reminders are computed the same way,
+//! for the next occurrences of each event.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use super::recur::{occurrences, Recurrence, MS_PER_DAY, MS_PER_MINUTE};
+use crate::services::time::tz::TimeZone;
+
+/// Storage path for events
+pub const CALENDAR_PATH: &str = "/system/settings/calendar.json";
+
+/// Maximum events stored (Rule 11: resource limits)
+pub const MAX_EVENTS: usize = 1024;
+
+/// Maximum occurrences returned by one query
+pub const MAX_QUERY_OCCURRENCES: usize = 500;
+
+/// Maximum title and calendar name length in bytes
+pub const MAX_TITLE_LEN: usize = 256;
+
+/// Maximum description and location length in bytes
+pub const MAX_TEXT_LEN: usize = 4096;
+
+/// Maximum reminders per event
+pub const MAX_REMINDERS: usize = 4;
+
+/// Earliest reminder, in minutes before the start (four weeks)
+pub const MAX_REMINDER_MINUTES: u32 = 4 * 7 * 24 * 60;
+
+/// Maximum excluded dates per event
+pub const MAX_EXDATES: usize = 64;
+
+/// Calendar of events that don't name one
+pub const DEFAULT_CALENDAR: &str = "default";
+
+/// How far ahead reminders are looked for.
+const REMINDER_HORIZON_MS: u64 = 400 * MS_PER_DAY as u64;
+
+fn default_calendar() -> String {
+    String::from(DEFAULT_CALENDAR)
+}
+
+fn default_timezone() -> String {
+    String::from("UTC")
+}
+
+/// A stored event, recurring or not.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    pub id: u32,
+    /// iCalendar UID, which identifies the event across imports
+    pub uid: String,
+    #[serde(flatten)]
+    pub fields: EventFields,
+}
+
+/// The fields of an event that clients set.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventFields {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub location: String,
+    /// Calendar the event belongs to (e.g. "work", "holidays")
+    #[serde(default = "default_calendar")]
+    pub calendar: String,
+    /// Start of the (first) occurrence, ms since the Unix epoch
+    pub start_ms: u64,
+    /// End of the (first) occurrence; the start if absent
+    #[serde(default)]
+    pub end_ms: Option<u64>,
+    #[serde(default)]
+    pub all_day: bool,
+    /// Zone whose wall-clock time a recurring event keeps
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// Start times of occurrences that were removed from the series
+    #[serde(default)]
+    pub exdates_ms: Vec<u64>,
+    /// Reminders, in minutes before each occurrence starts
+    #[serde(default)]
+    pub reminders_min: Vec<u32>,
+}
+
+impl EventFields {
+    fn duration_ms(&self) -> u64 {
+        self.end_ms
+            .unwrap_or(self.start_ms)
+            .saturating_sub(self.start_ms)
+    }
+
+    /// The zone occurrences are expanded in.
+    fn zone(&self) -> TimeZone {
+        let name = if self.all_day { "UTC" } else { &self.timezone };
+        // Validated on the way in; fall back to UTC should it ever go away
+        TimeZone::lookup(name).unwrap_or_else(TimeZone::utc)
+    }
+
+    /// Occurrences starting in `[from_ms, to_ms)`, at most `limit`.
+    fn starts(&self, from_ms: u64, to_ms: u64, limit: usize) -> Vec<u64> {
+        let mut starts = occurrences(
+            self.start_ms,
+            self.recurrence.as_ref(),
+            &self.zone(),
+            from_ms,
+            to_ms,
+            limit.saturating_add(self.exdates_ms.len()),
+        );
+        starts.retain(|start| !self.exdates_ms.contains(start));
+        starts.truncate(limit);
+        starts
+    }
+
+    /// Validate, replacing the zone name with the canonical one.
+    fn validate(mut self) -> Result<Self, String> {
+        if self.title.trim().is_empty() {
+            return Err(String::from("Title is required"));
+        }
+        if self.title.len() > MAX_TITLE_LEN || self.calendar.len() > MAX_TITLE_LEN {
+            return Err(format!("Title too long (max {} bytes)", MAX_TITLE_LEN));
+        }
+        if self.description.len() > MAX_TEXT_LEN || self.location.len() > MAX_TEXT_LEN {
+            return Err(format!("Text too long (max {} bytes)", MAX_TEXT_LEN));
+        }
+        if self.end_ms.is_some_and(|end| end < self.start_ms) {
+            return Err(String::from("Event ends before it starts"));
+        }
+        let zone = TimeZone::lookup(&self.timezone)
+            .ok_or_else(|| format!("Unknown timezone: {}", self.timezone))?;
+        self.timezone = zone.name;
+        if let Some(rule) = &self.recurrence {
+            rule.validate()?;
+        }
+        if self.exdates_ms.len() > MAX_EXDATES {
+            return Err(format!("Too many excluded dates (max {})", MAX_EXDATES));
+        }
+        if self.reminders_min.len() > MAX_REMINDERS {
+            return Err(format!("Too many reminders (max {})", MAX_REMINDERS));
+        }
+        if self.reminders_min.iter().any(|&m| m > MAX_REMINDER_MINUTES) {
+            return Err(format!(
+                "Reminders can be at most {} minutes early",
+                MAX_REMINDER_MINUTES
+            ));
+        }
+        self.reminders_min.sort_unstable();
+        self.reminders_min.dedup();
+        Ok(self)
+    }
+}
+
+/// One occurrence of an event, as returned by a query.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Occurrence {
+    pub event_id: u32,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub title: String,
+    pub description: String,
+    pub location: String,
+    pub calendar: String,
+    pub all_day: bool,
+    pub recurring: bool,
+}
+
+/// A reminder due for one occurrence of an event.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Reminder {
+    /// When to remind, ms since the Unix epoch
+    pub fire_at_ms: u64,
+    pub event_id: u32,
+    /// Start of the occurrence
+    pub start_ms: u64,
+    /// Alarm label: the title and how soon the event starts
+    pub label: String,
+}
+
+/// Reminder label for `title` starting `minutes` after the reminder.
+fn reminder_label(title: &str, minutes: u32) -> String {
+    match minutes {
+        0 => String::from(title),
+        m if m % (24 * 60) == 0 => format!("{} in {} d", title, m / (24 * 60)),
+        m if m % 60 == 0 => format!("{} in {} h", title, m / 60),
+        m => format!("{} in {} min", title, m),
+    }
+}
+
+/// Outcome of an import.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MergeCounts {
+    pub imported: u32,
+    pub updated: u32,
+}
+
+/// All stored events.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EventStore {
+    events: BTreeMap<u32, Event>,
+    next_id: u32,
+}
+
+/// Stored form of [`EventStore`]
+#[derive(Default, Serialize, Deserialize)]
+struct SavedEvents {
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+impl EventStore {
+    /// Serialize to JSON bytes
+    pub fn to_json(&self) -> Vec<u8> {
+        let saved = SavedEvents {
+            events: self.events.values().cloned().collect(),
+        };
+        serde_json::to_vec(&saved).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        let saved: SavedEvents = serde_json::from_slice(data).ok()?;
+        let mut store = Self::default();
+        for event in saved.events.into_iter().take(MAX_EVENTS) {
+            store.next_id = store.next_id.max(event.id.wrapping_add(1));
+            store.events.insert(event.id, event);
+        }
+        Some(store)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Store a new event. `uid` is generated if not given.
+    pub fn add(&mut self, fields: EventFields, uid: Option<String>) -> Result<Event, String> {
+        let fields = fields.validate()?;
+        if self.events.len() >= MAX_EVENTS {
+            return Err(format!("Event limit reached (max {})", MAX_EVENTS));
+        }
+        let id = self.alloc_id();
+        let event = Event {
+            id,
+            uid: uid.unwrap_or_else(|| format!("{}-{}@zero-os", id, fields.start_ms)),
+            fields,
+        };
+        self.events.insert(id, event.clone());
+        Ok(event)
+    }
+
+    /// Replace the fields of event `id`.
+    pub fn update(&mut self, id: u32, fields: EventFields) -> Result<Event, String> {
+        let fields = fields.validate()?;
+        let event = self
+            .events
+            .get_mut(&id)
+            .ok_or_else(|| format!("No event {}", id))?;
+        event.fields = fields;
+        Ok(event.clone())
+    }
+
+    /// Delete event `id`.
+    pub fn delete(&mut self, id: u32) -> Result<Event, String> {
+        self.events
+            .remove(&id)
+            .ok_or_else(|| format!("No event {}", id))
+    }
+
+    /// Store imported events, replacing those with the same UID.
+    ///
+    /// Returns the counts and, for each event that was refused, why.
+    pub fn merge(&mut self, imported: Vec<(String, EventFields)>) -> (MergeCounts, Vec<String>) {
+        let mut counts = MergeCounts::default();
+        let mut refused = Vec::new();
+        for (uid, fields) in imported {
+            let existing = self.events.values().find(|e| e.uid == uid).map(|e| e.id);
+            let result = match existing {
+                Some(id) => self.update(id, fields).map(|_| counts.updated += 1),
+                None => self
+                    .add(fields, Some(uid.clone()))
+                    .map(|_| counts.imported += 1),
+            };
+            if let Err(e) = result {
+                refused.push(format!("{}: {}", uid, e));
+            }
+        }
+        (counts, refused)
+    }
+
+    fn alloc_id(&mut self) -> u32 {
+        // At most MAX_EVENTS IDs are taken, so this finds one quickly
+        while self.events.contains_key(&self.next_id) || self.next_id == 0 {
+            self.next_id = self.next_id.wrapping_add(1);
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        id
+    }
+
+    /// Occurrences overlapping `[from_ms, to_ms)`, soonest first, and
+    /// whether more were left out.
+    pub fn query(
+        &self,
+        from_ms: u64,
+        to_ms: u64,
+        calendar: Option<&str>,
+    ) -> (Vec<Occurrence>, bool) {
+        let mut found = Vec::new();
+        let mut truncated = false;
+        for event in self.events.values() {
+            let f = &event.fields;
+            if calendar.is_some_and(|c| c != f.calendar) {
+                continue;
+            }
+            let duration = f.duration_ms();
+            // An occurrence overlaps if it starts before the end and ends
+            // after the start; zero-length ones count at their start
+            let earliest = from_ms
+                .saturating_sub(duration)
+                .saturating_add(1)
+                .min(from_ms);
+            let starts = f.starts(earliest, to_ms, MAX_QUERY_OCCURRENCES + 1);
+            if starts.len() > MAX_QUERY_OCCURRENCES {
+                truncated = true;
+            }
+            for start in starts {
+                found.push(Occurrence {
+                    event_id: event.id,
+                    start_ms: start,
+                    end_ms: start + duration,
+                    title: f.title.clone(),
+                    description: f.description.clone(),
+                    location: f.location.clone(),
+                    calendar: f.calendar.clone(),
+                    all_day: f.all_day,
+                    recurring: f.recurrence.is_some(),
+                });
+            }
+        }
+        found.sort_by_key(|o| (o.start_ms, o.event_id));
+        if found.len() > MAX_QUERY_OCCURRENCES {
+            found.truncate(MAX_QUERY_OCCURRENCES);
+            truncated = true;
+        }
+        (found, truncated)
+    }
+
+    /// The `limit` soonest reminders due after `now_ms`.
+    pub fn next_reminders(&self, now_ms: u64, limit: usize) -> Vec<Reminder> {
+        let mut reminders = Vec::new();
+        for event in self.events.values() {
+            let f = &event.fields;
+            for &minutes in &f.reminders_min {
+                let lead = minutes as u64 * MS_PER_MINUTE as u64;
+                let from = now_ms.saturating_add(lead).saturating_add(1);
+                for start in f.starts(from, from.saturating_add(REMINDER_HORIZON_MS), limit) {
+                    reminders.push(Reminder {
+                        fire_at_ms: start - lead,
+                        event_id: event.id,
+                        start_ms: start,
+                        label: reminder_label(&f.title, minutes),
+                    });
+                }
+            }
+        }
+        reminders.sort();
+        reminders.truncate(limit);
+        reminders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::recur::Frequency;
+    use super::*;
+
+    const HOUR: u64 = 3_600_000;
+
+    fn fields(title: &str, start_ms: u64) -> EventFields {
+        serde_json::from_value(serde_json::json!({
+            "title": title,
+            "start_ms": start_ms,
+            "end_ms": start_ms + HOUR,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_add_update_delete() {
+        let mut store = EventStore::default();
+        let event = store.add(fields("Standup", 10 * HOUR), None).unwrap();
+        assert_eq!(event.fields.calendar, DEFAULT_CALENDAR);
+        assert!(!event.uid.is_empty());
+
+        let mut changed = fields("Standup (moved)", 11 * HOUR);
+        changed.timezone = String::from("europe/berlin");
+        let updated = store.update(event.id, changed).unwrap();
+        assert_eq!(updated.uid, event.uid);
+        assert_eq!(updated.fields.timezone, "Europe/Berlin");
+
+        let mut backwards = fields("Oops", 10 * HOUR);
+        backwards.end_ms = Some(HOUR);
+        assert!(store.add(backwards, None).is_err());
+        assert!(store.add(fields(" ", HOUR), None).is_err());
+
+        store.delete(event.id).unwrap();
+        assert!(store.delete(event.id).is_err());
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_query_expands_and_excludes() {
+        let mut store = EventStore::default();
+        let day = MS_PER_DAY as u64;
+        let mut daily = fields("Gym", 7 * HOUR);
+        daily.recurrence = Some(Recurrence {
+            freq: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until_ms: None,
+            by_day: Vec::new(),
+        });
+        daily.exdates_ms = alloc::vec![day + 7 * HOUR];
+        daily.calendar = String::from("personal");
+        let gym = store.add(daily, None).unwrap();
+        store.add(fields("Lunch", 12 * HOUR), None).unwrap();
+
+        let (found, truncated) = store.query(0, 3 * day, None);
+        assert!(!truncated);
+        let starts: Vec<u64> = found.iter().map(|o| o.start_ms).collect();
+        assert_eq!(starts, alloc::vec![7 * HOUR, 12 * HOUR, 2 * day + 7 * HOUR]);
+
+        // An occurrence already under way overlaps the range
+        let (found, _) = store.query(7 * HOUR + 1, 8 * HOUR, Some("personal"));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].event_id, gym.id);
+        assert!(found[0].recurring);
+
+        let (found, truncated) = store.query(0, 1000 * day, Some("personal"));
+        assert!(truncated);
+        assert_eq!(found.len(), MAX_QUERY_OCCURRENCES);
+    }
+
+    #[test]
+    fn test_next_reminders() {
+        let mut store = EventStore::default();
+        let mut meeting = fields("Review", 10 * HOUR);
+        meeting.reminders_min = alloc::vec![60, 10];
+        store.add(meeting, None).unwrap();
+
+        let reminders = store.next_reminders(9 * HOUR + 30 * 60_000, 8);
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].fire_at_ms, 10 * HOUR - 10 * 60_000);
+        assert_eq!(reminders[0].label, "Review in 10 min");
+        assert_eq!(store.next_reminders(0, 8)[0].label, "Review in 1 h");
+    }
+
+    #[test]
+    fn test_merge_by_uid_and_json_round_trip() {
+        let mut store = EventStore::default();
+        let batch = alloc::vec![
+            (String::from("a@example"), fields("A", HOUR)),
+            (String::from("b@example"), fields("", HOUR)),
+        ];
+        let (counts, refused) = store.merge(batch);
+        assert_eq!(
+            counts,
+            MergeCounts {
+                imported: 1,
+                updated: 0
+            }
+        );
+        assert_eq!(refused.len(), 1);
+
+        let (counts, _) = store.merge(alloc::vec![(String::from("a@example"), fields("A2", HOUR))]);
+        assert_eq!(
+            counts,
+            MergeCounts {
+                imported: 0,
+                updated: 1
+            }
+        );
+        assert_eq!(store.len(), 1);
+
+        let mut saved = EventStore::from_json(&store.to_json()).unwrap();
+        assert_eq!(saved, store);
+        // IDs carry on after the stored ones
+        let next = saved.add(fields("B", HOUR), None).unwrap();
+        assert_eq!(next.id, 2);
+        assert_eq!(saved.len(), 2);
+    }
+}
@@ -0,0 +1,176 @@
+//! Clipboard history
+//!
+//! The clipboard is the newest entry of a short history kept in memory
+//! only: nothing copied is written to storage, so the history is gone
+//! after a restart. Copying text already in the history moves it to the
+//! top instead of adding it twice.
+//!
+//! An app can mark what it copies as sensitive (a password, a one-time
+//! code). A sensitive entry can be pasted while it is the clipboard but is
+//! never listed, and it is dropped as soon as something else is copied.
+
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Serialize;
+
+/// Maximum entries kept; the oldest are dropped first (Rule 11)
+pub const MAX_HISTORY: usize = 25;
+
+/// Maximum entry length in bytes
+pub const MAX_ENTRY_BYTES: usize = 64 * 1024;
+
+/// Maximum source name length in bytes
+pub const MAX_SOURCE_LEN: usize = 64;
+
+/// One copied piece of text.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ClipboardEntry {
+    pub id: u64,
+    pub text: String,
+    /// App it was copied in, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// When it was copied, ms since the Unix epoch
+    pub copied_at_ms: u64,
+    /// Copied on another of the user's machines
+    pub remote: bool,
+    /// Marked sensitive by the app that copied it
+    pub sensitive: bool,
+}
+
+/// Where an entry comes from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Origin {
+    pub source: Option<String>,
+    pub remote: bool,
+    pub sensitive: bool,
+}
+
+/// Recent clipboard entries, newest first.
+#[derive(Debug, Default)]
+pub struct ClipboardHistory {
+    next_id: u64,
+    entries: VecDeque<ClipboardEntry>,
+}
+
+impl ClipboardHistory {
+    /// Make `text` the clipboard. Returns the new entry.
+    pub fn copy(
+        &mut self,
+        text: String,
+        origin: Origin,
+        now_ms: u64,
+    ) -> Result<&ClipboardEntry, String> {
+        if text.is_empty() {
+            return Err(String::from("Nothing to copy"));
+        }
+        if text.len() > MAX_ENTRY_BYTES {
+            return Err(format!(
+                "Clipboard entry too large (max {} bytes)",
+                MAX_ENTRY_BYTES
+            ));
+        }
+        if origin
+            .source
+            .as_ref()
+            .is_some_and(|s| s.len() > MAX_SOURCE_LEN)
+        {
+            return Err(format!("Source too long (max {} bytes)", MAX_SOURCE_LEN));
+        }
+
+        self.entries.retain(|e| !e.sensitive && e.text != text);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.entries.push_front(ClipboardEntry {
+            id: self.next_id,
+            text,
+            source: origin.source,
+            copied_at_ms: now_ms,
+            remote: origin.remote,
+            sensitive: origin.sensitive,
+        });
+        self.entries.truncate(MAX_HISTORY);
+        Ok(&self.entries[0])
+    }
+
+    /// The clipboard: the newest entry.
+    pub fn current(&self) -> Option<&ClipboardEntry> {
+        self.entries.front()
+    }
+
+    /// Up to `limit` entries, newest first, leaving out sensitive ones.
+    pub fn recent(&self, limit: usize) -> Vec<ClipboardEntry> {
+        self.entries
+            .iter()
+            .filter(|e| !e.sensitive)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing has been copied.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn copy(history: &mut ClipboardHistory, text: &str, sensitive: bool) -> u64 {
+        let origin = Origin {
+            sensitive,
+            ..Default::default()
+        };
+        history.copy(String::from(text), origin, 1).unwrap().id
+    }
+
+    #[test]
+    fn test_copy_moves_repeats_to_top() {
+        let mut history = ClipboardHistory::default();
+        copy(&mut history, "a", false);
+        copy(&mut history, "b", false);
+        let id = copy(&mut history, "a", false);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.current().unwrap().id, id);
+        let texts: Vec<String> = history.recent(10).into_iter().map(|e| e.text).collect();
+        assert_eq!(texts, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_sensitive_entries_not_kept() {
+        let mut history = ClipboardHistory::default();
+        copy(&mut history, "a", false);
+        copy(&mut history, "hunter2", true);
+        assert_eq!(history.current().unwrap().text, "hunter2");
+        assert_eq!(history.recent(10).len(), 1);
+
+        copy(&mut history, "b", false);
+        assert_eq!(history.len(), 2);
+        assert!(history.recent(10).iter().all(|e| e.text != "hunter2"));
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut history = ClipboardHistory::default();
+        let too_large = "x".repeat(MAX_ENTRY_BYTES + 1);
+        assert!(history.copy(too_large, Origin::default(), 1).is_err());
+        assert!(history.copy(String::new(), Origin::default(), 1).is_err());
+
+        for i in 0..MAX_HISTORY + 5 {
+            copy(&mut history, &format!("entry {}", i), false);
+        }
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(
+            history.recent(1)[0].text,
+            format!("entry {}", MAX_HISTORY + 4)
+        );
+    }
+}
@@ -0,0 +1,718 @@
+//! Clipboard Service
+//!
+//! The ClipboardService owns the clipboard, so copy and paste work between
+//! apps without any of them holding what another copied. It:
+//! - Keeps a short in-memory history of copied text (see [`history`])
+//! - Sends entries to the user's other enrolled machines through the
+//!   MessagingService, which seals them end-to-end, when sync is turned on
+//!   for this machine (see [`sync`])
+//! - Applies entries copied on those machines when sync is on here too
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - COPY: Entry is the clipboard AND, if sync allows it, handed to the
+//!   MessagingService
+//! - SET_SYNC: Settings validated AND written to VFS AND then in effect
+//!
+//! **Acceptable partial failure:**
+//! - Messaging unreachable or no mailbox open → the entry stays on this
+//!   machine
+//! - Settings not loaded yet → nothing is synced until they are
+//!
+//! **Forbidden:**
+//! - Sending anything while sync is off, or an entry a sync rule excludes
+//! - Applying entries from anyone but the MessagingService, or while sync
+//!   is off
+//! - Apps reading the history or changing sync settings (desktop and
+//!   system processes only)
+//! - Writing clipboard contents to storage
+//! - Unbounded history growth (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_CLIPBOARD_COPY (0x8E00)`: Copy text
+//! - `MSG_CLIPBOARD_PASTE (0x8E02)`: Get the clipboard
+//! - `MSG_CLIPBOARD_HISTORY (0x8E04)`: List recent entries
+//! - `MSG_CLIPBOARD_GET_SYNC (0x8E06)`: Get this machine's sync settings
+//! - `MSG_CLIPBOARD_SET_SYNC (0x8E08)`: Change them (Settings)
+//! - `MSG_CLIPBOARD_REMOTE (0x8E0A)`: Entry from another machine
+//!
+//! # Sync
+//!
+//! Entries go out as `MSG_MESSAGING_SEND_CLIPBOARD`, sent to the
+//! MessagingService by name; the supervisor routes its replies back here
+//! via Init. The MessagingService only accepts clipboard entries from this
+//! service, and hands those it receives from the user's machines back as
+//! `MSG_CLIPBOARD_REMOTE`.
+//!
+//! # Storage Access
+//!
+//! This service uses VFS IPC (async/await pattern, see
+//! [`zos_vfs::client::futures`]) to persist its sync settings. All storage
+//! operations flow through the VFS service per Invariant 31.
+
+extern crate alloc;
+
+pub mod history;
+pub mod sync;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+use zos_ipc::messaging;
+use zos_vfs::client::futures::VfsExecutor;
+
+use crate::manifests::CLIPBOARD_MANIFEST;
+use crate::response::JsonResponder;
+use crate::trust::{CallerClass, CallerNames};
+
+pub use history::{ClipboardEntry, ClipboardHistory, Origin, MAX_ENTRY_BYTES, MAX_HISTORY};
+pub use sync::{Exclusion, SyncSettings, SyncUpdate, MAX_SYNC_BYTES, SETTINGS_PATH};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for clipboard service - re-exported from zos-ipc.
+pub mod clipboard_msg {
+    pub use zos_ipc::clipboard::*;
+}
+
+/// Sync settings loads and saves in flight at once (Rule 11)
+const MAX_VFS_TASKS: usize = 4;
+
+/// Entries returned by MSG_CLIPBOARD_HISTORY without a limit
+const DEFAULT_HISTORY_LIMIT: usize = 10;
+
+// =============================================================================
+// Request Types
+// =============================================================================
+
+/// Payload of MSG_CLIPBOARD_COPY.
+#[derive(Clone, Debug, Deserialize)]
+struct CopyRequest {
+    text: String,
+    /// App copied in; only trusted from the desktop and system processes
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    sensitive: bool,
+}
+
+/// Payload of MSG_CLIPBOARD_HISTORY.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct HistoryRequest {
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Payload of MSG_CLIPBOARD_REMOTE.
+#[derive(Clone, Debug, Deserialize)]
+struct RemoteEntry {
+    text: String,
+    copied_at_ms: u64,
+}
+
+/// A finished VFS task.
+enum Done {
+    /// The sync settings read on startup
+    Loaded(Result<Vec<u8>, String>),
+    /// A write of changed sync settings, which take effect once written
+    Saved {
+        client_pid: u32,
+        cap_slots: Vec<u32>,
+        settings: SyncSettings,
+        result: Result<(), String>,
+    },
+}
+
+// =============================================================================
+// ClipboardService Application
+// =============================================================================
+
+/// ClipboardService - clipboard history with opt-in sync between machines
+pub struct ClipboardService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// What was copied, newest first
+    history: ClipboardHistory,
+    /// This machine's sync settings
+    settings: SyncSettings,
+    /// Whether the settings have been loaded; until then nothing is synced
+    /// and they can't be changed
+    loaded: bool,
+    /// VFS reads and writes in progress
+    vfs: VfsExecutor<Done>,
+    /// Names callers run under, for trusting them by name
+    names: CallerNames,
+}
+
+impl Default for ClipboardService {
+    fn default() -> Self {
+        Self {
+            registered: false,
+            history: ClipboardHistory::default(),
+            settings: SyncSettings::default(),
+            loaded: false,
+            vfs: VfsExecutor::new(),
+            names: CallerNames::default(),
+        }
+    }
+}
+
+impl ClipboardService {
+    /// Whether the caller is the desktop or a system process (Rule 4:
+    /// fail-closed).
+    fn is_trusted(&self, from_pid: u32) -> bool {
+        let class = self.names.class_of(from_pid);
+        class.is_system() || class == CallerClass::Desktop
+    }
+
+    /// Whether another settings load or save may start.
+    fn check_task_limit(&self) -> bool {
+        if self.vfs.running() >= MAX_VFS_TASKS {
+            syscall::debug(&format!(
+                "ClipboardService: VFS task limit reached ({}/{})",
+                self.vfs.running(),
+                MAX_VFS_TASKS
+            ));
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Hand the clipboard to the MessagingService if sync allows it.
+    fn sync_current(&self) -> Result<(), Exclusion> {
+        let Some(entry) = self.history.current() else {
+            return Ok(());
+        };
+        if !self.loaded {
+            return Err(Exclusion::Disabled);
+        }
+        self.settings.check_outgoing(entry)?;
+
+        let json = serde_json::to_vec(&serde_json::json!({
+            "text": entry.text,
+            "copied_at_ms": entry.copied_at_ms,
+        }))
+        .unwrap_or_default();
+        if let Err(e) =
+            syscall::send_named("messaging", messaging::MSG_MESSAGING_SEND_CLIPBOARD, &json)
+        {
+            syscall::debug(&format!(
+                "ClipboardService: Entry {} not synced: {}",
+                entry.id, e
+            ));
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_CLIPBOARD_COPY
+    fn handle_copy(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let tag = clipboard_msg::MSG_CLIPBOARD_COPY_RESPONSE;
+        let request: CopyRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid copy request: expected {\"text\": string, \"sensitive\": bool?}",
+                );
+            }
+        };
+        // Apps are known by the name they run under, so an app can't dodge
+        // an excluded source by naming another
+        let source = if self.is_trusted(msg.from_pid) {
+            request.source
+        } else {
+            self.names.name_of(msg.from_pid)
+        };
+        let origin = Origin {
+            source,
+            remote: false,
+            sensitive: request.sensitive,
+        };
+
+        let id = match self.history.copy(request.text, origin, now_ms) {
+            Ok(entry) => entry.id,
+            Err(e) => return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        };
+        let response = match self.sync_current() {
+            Ok(()) => serde_json::json!({ "id": id, "synced": true }),
+            Err(excluded) => serde_json::json!({ "id": id, "synced": false, "excluded": excluded }),
+        };
+        let json = serde_json::to_vec(&response).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_CLIPBOARD_PASTE
+    fn handle_paste(&mut self, msg: &Message) -> Result<(), AppError> {
+        let json = serde_json::to_vec(&serde_json::json!({ "entry": self.history.current() }))
+            .unwrap_or_default();
+        self.send_response(
+            msg.from_pid,
+            &msg.cap_slots,
+            clipboard_msg::MSG_CLIPBOARD_PASTE_RESPONSE,
+            &json,
+        )
+    }
+
+    /// Handle MSG_CLIPBOARD_HISTORY
+    fn handle_history(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = clipboard_msg::MSG_CLIPBOARD_HISTORY_RESPONSE;
+        if !self.is_trusted(msg.from_pid) {
+            syscall::debug(&format!(
+                "ClipboardService: SECURITY - HISTORY denied for PID {}",
+                msg.from_pid
+            ));
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: HISTORY requires desktop or system privilege",
+            );
+        }
+        let request: HistoryRequest = if msg.data.is_empty() {
+            HistoryRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid history request: expected {\"limit\": u32?}",
+                    );
+                }
+            }
+        };
+        let limit = request
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .min(MAX_HISTORY);
+        let json =
+            serde_json::to_vec(&serde_json::json!({ "entries": self.history.recent(limit) }))
+                .unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_CLIPBOARD_GET_SYNC
+    fn handle_get_sync(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = clipboard_msg::MSG_CLIPBOARD_GET_SYNC_RESPONSE;
+        if !self.loaded {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Service busy: settings are loading",
+            );
+        }
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &self.settings.to_json())
+    }
+
+    /// Handle MSG_CLIPBOARD_SET_SYNC
+    fn handle_set_sync(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = clipboard_msg::MSG_CLIPBOARD_SET_SYNC_RESPONSE;
+        if !self.is_trusted(msg.from_pid) {
+            syscall::debug(&format!(
+                "ClipboardService: SECURITY - SET_SYNC denied for PID {}",
+                msg.from_pid
+            ));
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: SET_SYNC requires desktop or system privilege",
+            );
+        }
+        // Settings changed before the load would be replaced by it
+        if !self.loaded {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Service busy: settings are loading",
+            );
+        }
+        let update: SyncUpdate = match serde_json::from_slice(&msg.data) {
+            Ok(u) => u,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid sync settings: expected {\"enabled\": bool?, \"max_bytes\": u32?, \"excluded_sources\": [string]?}",
+                );
+            }
+        };
+        let settings = match self.settings.apply(update) {
+            Ok(s) => s,
+            Err(e) => return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        };
+        if !self.check_task_limit() {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Service busy: too many settings changes in progress",
+            );
+        }
+
+        let value = settings.to_json();
+        let vfs = self.vfs.handle();
+        let client_pid = msg.from_pid;
+        let cap_slots = msg.cap_slots.clone();
+        self.vfs.spawn(async move {
+            let result = vfs.write(SETTINGS_PATH, &value).await;
+            Done::Saved {
+                client_pid,
+                cap_slots,
+                settings,
+                result,
+            }
+        });
+        // Fails right away if the VFS can't be reached
+        self.finish_tasks();
+        Ok(())
+    }
+
+    /// Handle MSG_CLIPBOARD_REMOTE
+    fn handle_remote(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        // Rule 10: entries from other machines only arrive through messaging
+        if !self.names.is(msg.from_pid, "messaging") {
+            syscall::debug(&format!(
+                "ClipboardService: SECURITY - Dropping remote entry from PID {}",
+                msg.from_pid
+            ));
+            return Ok(());
+        }
+        let Ok(entry) = serde_json::from_slice::<RemoteEntry>(&msg.data) else {
+            return Ok(());
+        };
+        if !self.loaded {
+            return Ok(());
+        }
+        if let Err(excluded) = self
+            .settings
+            .check_incoming(&entry.text, entry.copied_at_ms, now_ms)
+        {
+            syscall::debug(&format!(
+                "ClipboardService: Remote entry not applied ({:?})",
+                excluded
+            ));
+            return Ok(());
+        }
+        let origin = Origin {
+            source: None,
+            remote: true,
+            sensitive: false,
+        };
+        if let Err(e) = self.history.copy(entry.text, origin, entry.copied_at_ms) {
+            syscall::debug(&format!(
+                "ClipboardService: Remote entry not applied: {}",
+                e
+            ));
+        }
+        Ok(())
+    }
+
+    /// Handle MSG_MESSAGING_SEND_CLIPBOARD_RESPONSE
+    fn handle_sent(&mut self, msg: &Message) -> Result<(), AppError> {
+        let response: serde_json::Value = serde_json::from_slice(&msg.data).unwrap_or_default();
+        if let Some(error) = response.get("error").and_then(|e| e.as_str()) {
+            syscall::debug(&format!("ClipboardService: Entry not synced: {}", error));
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Handle a VFS response to one of our tasks. Returns false if the
+    /// message isn't one.
+    fn handle_vfs_response(&mut self, msg: &Message) -> bool {
+        if !self.vfs.on_message(msg.tag, &msg.data) {
+            return false;
+        }
+        self.finish_tasks();
+        true
+    }
+
+    /// Handle the outcome of every finished VFS task.
+    fn finish_tasks(&mut self) {
+        while let Some(done) = self.vfs.next_finished() {
+            match done {
+                Done::Loaded(result) => {
+                    match result.ok().and_then(|data| SyncSettings::from_json(&data)) {
+                        Some(stored) => {
+                            syscall::debug(&format!(
+                                "ClipboardService: Sync is {}",
+                                if stored.enabled { "on" } else { "off" }
+                            ));
+                            self.settings = stored;
+                        }
+                        None => syscall::debug("ClipboardService: No sync settings, sync is off"),
+                    }
+                    self.loaded = true;
+                }
+
+                Done::Saved {
+                    client_pid,
+                    cap_slots,
+                    settings,
+                    result,
+                } => {
+                    let tag = clipboard_msg::MSG_CLIPBOARD_SET_SYNC_RESPONSE;
+                    let sent = match result {
+                        Ok(()) => {
+                            syscall::debug(&format!(
+                                "ClipboardService: PID {} turned sync {}",
+                                client_pid,
+                                if settings.enabled { "on" } else { "off" }
+                            ));
+                            self.settings = settings;
+                            self.send_response(
+                                client_pid,
+                                &cap_slots,
+                                tag,
+                                &self.settings.to_json(),
+                            )
+                        }
+                        // Rule 9: Include operation context in error
+                        Err(e) => self.send_error_response(
+                            client_pid,
+                            &cap_slots,
+                            tag,
+                            &format!("VFS write failed for {}: {}", SETTINGS_PATH, e),
+                        ),
+                    };
+                    if let Err(e) = sent {
+                        syscall::debug(&format!(
+                            "ClipboardService: Failed to answer PID {}: {:?}",
+                            client_pid, e
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl JsonResponder for ClipboardService {
+    const SERVICE_NAME: &'static str = "ClipboardService";
+}
+
+impl ZeroApp for ClipboardService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &CLIPBOARD_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("ClipboardService starting (PID {})", ctx.pid));
+
+        // Register with init as "clipboard" service
+        let service_name = "clipboard";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("ClipboardService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+
+        // Load this machine's sync settings via VFS
+        let vfs = self.vfs.handle();
+        self.vfs
+            .spawn(async move { Done::Loaded(vfs.read(SETTINGS_PATH).await) });
+        self.finish_tasks();
+
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        // VFS responses (Invariant 31 compliant - storage via VFS IPC)
+        if self.handle_vfs_response(&msg) {
+            return Ok(());
+        }
+
+        match msg.tag {
+            // MessagingService replies
+            messaging::MSG_MESSAGING_SEND_CLIPBOARD_RESPONSE => self.handle_sent(&msg),
+
+            // Clipboard service protocol
+            clipboard_msg::MSG_CLIPBOARD_COPY => self.handle_copy(&msg, ctx.wallclock_ms),
+            clipboard_msg::MSG_CLIPBOARD_PASTE => self.handle_paste(&msg),
+            clipboard_msg::MSG_CLIPBOARD_HISTORY => self.handle_history(&msg),
+            clipboard_msg::MSG_CLIPBOARD_GET_SYNC => self.handle_get_sync(&msg),
+            clipboard_msg::MSG_CLIPBOARD_SET_SYNC => self.handle_set_sync(&msg),
+            clipboard_msg::MSG_CLIPBOARD_REMOTE => self.handle_remote(&msg, ctx.wallclock_ms),
+
+            _ => {
+                syscall::debug(&format!(
+                    "ClipboardService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("ClipboardService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+
+    /// A service with settings loaded and sync on.
+    fn syncing() -> ClipboardService {
+        ClipboardService {
+            settings: SyncSettings {
+                enabled: true,
+                ..Default::default()
+            },
+            loaded: true,
+            ..Default::default()
+        }
+    }
+
+    fn copy(service: &mut ClipboardService, from_pid: u32, json: &str) {
+        let msg = mock_message(
+            clipboard_msg::MSG_CLIPBOARD_COPY,
+            from_pid,
+            json.as_bytes().to_vec(),
+        );
+        service.handle_copy(&msg, 1_000).unwrap();
+    }
+
+    fn remote(service: &mut ClipboardService, from_pid: u32, text: &str) {
+        let json = format!(r#"{{"text":"{}","copied_at_ms":900}}"#, text);
+        let msg = mock_message(
+            clipboard_msg::MSG_CLIPBOARD_REMOTE,
+            from_pid,
+            json.into_bytes(),
+        );
+        service.handle_remote(&msg, 1_000).unwrap();
+    }
+
+    #[test]
+    fn test_nothing_synced_until_loaded() {
+        let mut service = ClipboardService {
+            settings: SyncSettings {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        copy(&mut service, 20, r#"{"text":"hello"}"#);
+        assert_eq!(service.sync_current(), Err(Exclusion::Disabled));
+
+        service.loaded = true;
+        assert_eq!(service.sync_current(), Ok(()));
+    }
+
+    #[test]
+    fn test_app_source_is_its_name() {
+        let mut service = syncing();
+        service.settings.excluded_sources = vec![String::from("passwords")];
+        service.names.remember(20, "passwords");
+        service.names.remember(21, "notes");
+
+        copy(&mut service, 20, r#"{"text":"hello"}"#);
+        assert_eq!(service.sync_current(), Err(Exclusion::ExcludedSource));
+        // Naming another source changes nothing for an app
+        copy(&mut service, 20, r#"{"text":"again","source":"notes"}"#);
+        assert_eq!(service.sync_current(), Err(Exclusion::ExcludedSource));
+
+        copy(&mut service, 21, r#"{"text":"hello"}"#);
+        assert_eq!(service.sync_current(), Ok(()));
+        copy(&mut service, 21, r#"{"text":"pin","sensitive":true}"#);
+        assert_eq!(service.sync_current(), Err(Exclusion::MarkedSensitive));
+    }
+
+    #[test]
+    fn test_remote_entries_only_from_messaging() {
+        let mut service = syncing();
+        service.names.remember(9, "messaging");
+        service.names.remember(20, "notes");
+
+        remote(&mut service, 20, "forged");
+        assert!(service.history.is_empty());
+
+        remote(&mut service, 9, "from laptop");
+        let current = service.history.current().unwrap();
+        assert_eq!(current.text, "from laptop");
+        assert!(current.remote);
+        // Never sent back
+        assert_eq!(service.sync_current(), Err(Exclusion::Remote));
+
+        service.settings.enabled = false;
+        remote(&mut service, 9, "while off");
+        assert_eq!(service.history.len(), 1);
+    }
+
+    #[test]
+    fn test_sync_settings_take_effect_once_written() {
+        let mut service = ClipboardService {
+            loaded: true,
+            ..Default::default()
+        };
+        service.names.remember(3, "desktop");
+        service.names.remember(20, "notes");
+
+        let set = |from_pid| {
+            mock_message(
+                clipboard_msg::MSG_CLIPBOARD_SET_SYNC,
+                from_pid,
+                br#"{"enabled":true}"#.to_vec(),
+            )
+        };
+        service.handle_set_sync(&set(20)).unwrap();
+        assert_eq!(service.vfs.running(), 0);
+
+        service.handle_set_sync(&set(3)).unwrap();
+        assert_eq!(service.vfs.running(), 1);
+        assert!(!service.settings.enabled);
+
+        let written = mock_message(
+            zos_vfs::ipc::vfs_msg::MSG_VFS_WRITE_RESPONSE,
+            4,
+            br#"{"result":{"Ok":null}}"#.to_vec(),
+        );
+        assert!(service.handle_vfs_response(&written));
+        assert!(service.settings.enabled);
+    }
+}
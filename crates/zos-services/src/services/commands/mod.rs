@@ -0,0 +1,434 @@
+//! Command Service
+//!
+//! The CommandService powers the system-wide command palette. Apps declare
+//! named commands with keywords, either in their manifest or at runtime,
+//! and the palette searches all of them at once. It:
+//! - Keeps each process's commands, bounded per process and in total
+//! - Fuzzy-matches palette queries across every registered command
+//! - Hands an invoked command to the app that registered it
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - REGISTER: Every command in the request recorded for its owner (a
+//!   batch is registered whole or not at all)
+//! - INVOKE: Command handed to the supervisor for delivery to its owner
+//!
+//! **Acceptable partial failure:**
+//! - Owner exited → the invocation is dropped by Init; its commands linger
+//!   until unregistered, bounded by the per-process limit
+//!
+//! **Forbidden:**
+//! - A process registering or dropping commands in another's name (only
+//!   system processes may name the owner, for manifest commands)
+//! - Unprivileged processes invoking commands of other apps
+//! - Unbounded command growth (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_COMMAND_REGISTER (0x8D00)`: Register commands
+//! - `MSG_COMMAND_UNREGISTER (0x8D02)`: Drop one or all of the caller's commands
+//! - `MSG_COMMAND_QUERY (0x8D04)`: Fuzzy-match commands for the palette
+//! - `MSG_COMMAND_INVOKE (0x8D06)`: Invoke a command
+//! - `MSG_COMMAND (0x8D08)`: Invoked command delivered to its app
+//!
+//! Commands declared in an app's manifest are announced by the app runtime
+//! as `COMMAND:REGISTER:{hex}` on the debug channel; the supervisor adds the
+//! app's PID and forwards them as `MSG_COMMAND_REGISTER` through Init. The
+//! service holds no capabilities to apps, so invoked commands go the other
+//! way as `COMMAND:DELIVER:{pid}:{tag}:{hex}`, like event bus deliveries.
+
+extern crate alloc;
+
+pub mod registry;
+
+use crate::manifests::COMMANDS_MANIFEST;
+use crate::response::JsonResponder;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use serde_json::Value;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+
+pub use registry::{
+    CommandMatch, CommandRegistry, CommandSpec, RegistryError, DEFAULT_RESULTS,
+    MAX_COMMANDS_PER_PID, MAX_RESULTS,
+};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for command service - re-exported from zos-ipc.
+pub mod commands_msg {
+    pub use zos_ipc::commands::*;
+}
+
+// =============================================================================
+// Permission Constants
+// =============================================================================
+
+/// PIDs allowed to invoke commands and to name the owner of commands.
+/// - PID 0: Supervisor
+/// - PID 1: Init (manifest registrations and palette requests it delivers)
+/// - PID 3: Desktop
+const TRUSTED_PIDS: &[u32] = &[0, 1, 3];
+
+// =============================================================================
+// Request Types
+// =============================================================================
+
+/// Payload of MSG_COMMAND_REGISTER.
+#[derive(Clone, Debug, Deserialize)]
+struct RegisterRequest {
+    /// Owner, when registering for another process
+    #[serde(default)]
+    pid: Option<u32>,
+    #[serde(default)]
+    app: String,
+    commands: Vec<CommandSpec>,
+}
+
+/// Payload of MSG_COMMAND_UNREGISTER.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct UnregisterRequest {
+    /// Owner, when unregistering for another process
+    #[serde(default)]
+    pid: Option<u32>,
+    #[serde(default)]
+    id: Option<String>,
+}
+
+/// Payload of MSG_COMMAND_QUERY.
+#[derive(Clone, Debug, Deserialize)]
+struct QueryRequest {
+    #[serde(default)]
+    query: String,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Payload of MSG_COMMAND_INVOKE.
+#[derive(Clone, Debug, Deserialize)]
+struct InvokeRequest {
+    pid: u32,
+    id: String,
+    #[serde(default)]
+    args: Value,
+}
+
+// =============================================================================
+// CommandService Application
+// =============================================================================
+
+/// CommandService - command registry behind the command palette
+#[derive(Default)]
+pub struct CommandService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// Commands of every process
+    registry: CommandRegistry,
+}
+
+impl CommandService {
+    /// Check if caller is a system process (Rule 4: fail-closed).
+    fn is_trusted(from_pid: u32) -> bool {
+        TRUSTED_PIDS.contains(&from_pid)
+    }
+
+    /// Owner of the commands in a request: the caller, unless a trusted
+    /// caller names another process.
+    fn owner(&self, from_pid: u32, requested: Option<u32>) -> Option<u32> {
+        match requested {
+            None => Some(from_pid),
+            Some(pid) if pid == from_pid || Self::is_trusted(from_pid) => Some(pid),
+            Some(pid) => {
+                syscall::debug(&format!(
+                    "CommandService: SECURITY - PID {} may not act for PID {}",
+                    from_pid, pid
+                ));
+                None
+            }
+        }
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_COMMAND_REGISTER
+    fn handle_register(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = commands_msg::MSG_COMMAND_REGISTER_RESPONSE;
+        let request: RegisterRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid register request: expected {\"commands\": [{\"id\", \"title\"}]}",
+                );
+            }
+        };
+        let Some(owner) = self.owner(msg.from_pid, request.pid) else {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: cannot register commands for another process",
+            );
+        };
+
+        let registered = match self
+            .registry
+            .register(owner, &request.app, request.commands)
+        {
+            Ok(n) => n,
+            Err(e) => {
+                return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, e.message());
+            }
+        };
+        syscall::debug(&format!(
+            "CommandService: PID {} ({}) registered {} command(s)",
+            owner, request.app, registered
+        ));
+        let json = format!(r#"{{"registered":{}}}"#, registered);
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, json.as_bytes())
+    }
+
+    /// Handle MSG_COMMAND_UNREGISTER
+    fn handle_unregister(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = commands_msg::MSG_COMMAND_UNREGISTER_RESPONSE;
+        // An empty payload drops everything the caller registered
+        let request: UnregisterRequest = if msg.data.is_empty() {
+            UnregisterRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid unregister request: expected {\"id\": string?}",
+                    );
+                }
+            }
+        };
+        let Some(owner) = self.owner(msg.from_pid, request.pid) else {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: cannot unregister commands of another process",
+            );
+        };
+
+        let removed = self.registry.unregister(owner, request.id.as_deref());
+        let json = format!(r#"{{"removed":{}}}"#, removed);
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, json.as_bytes())
+    }
+
+    /// Handle MSG_COMMAND_QUERY
+    fn handle_query(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = commands_msg::MSG_COMMAND_QUERY_RESPONSE;
+        let request: QueryRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid query request: expected {\"query\": string}",
+                );
+            }
+        };
+
+        let limit = request.limit.unwrap_or(DEFAULT_RESULTS);
+        let results = match self.registry.query(&request.query, limit) {
+            Ok(r) => r,
+            Err(e) => {
+                return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, e.message());
+            }
+        };
+        let json =
+            serde_json::to_vec(&serde_json::json!({ "results": results })).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_COMMAND_INVOKE
+    fn handle_invoke(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = commands_msg::MSG_COMMAND_INVOKE_RESPONSE;
+
+        if !Self::is_trusted(msg.from_pid) {
+            syscall::debug(&format!(
+                "CommandService: SECURITY - INVOKE denied for PID {}",
+                msg.from_pid
+            ));
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: INVOKE requires system privilege",
+            );
+        }
+        let request: InvokeRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid invoke request: expected {\"pid\": u32, \"id\": string}",
+                );
+            }
+        };
+        if self.registry.get(request.pid, &request.id).is_none() {
+            return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, "Unknown command");
+        }
+
+        let command = serde_json::json!({ "id": request.id, "args": request.args });
+        let json = serde_json::to_vec(&command).unwrap_or_default();
+        let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+        syscall::debug(&format!(
+            "{}{}:{:08x}:{}",
+            zos_ipc::debug::COMMAND_DELIVER,
+            request.pid,
+            commands_msg::MSG_COMMAND,
+            hex
+        ));
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, br#"{"invoked":true}"#)
+    }
+}
+
+impl JsonResponder for CommandService {
+    const SERVICE_NAME: &'static str = "CommandService";
+}
+
+impl ZeroApp for CommandService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &COMMANDS_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("CommandService starting (PID {})", ctx.pid));
+
+        // Register with init as "commands" service
+        let service_name = "commands";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("CommandService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, _ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        match msg.tag {
+            commands_msg::MSG_COMMAND_REGISTER => self.handle_register(&msg),
+            commands_msg::MSG_COMMAND_UNREGISTER => self.handle_unregister(&msg),
+            commands_msg::MSG_COMMAND_QUERY => self.handle_query(&msg),
+            commands_msg::MSG_COMMAND_INVOKE => self.handle_invoke(&msg),
+            _ => {
+                syscall::debug(&format!(
+                    "CommandService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("CommandService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+
+    fn register(service: &mut CommandService, pid: u32, json: &str) {
+        let msg = mock_message(commands_msg::MSG_COMMAND_REGISTER, pid, json.into());
+        service.handle_register(&msg).unwrap();
+    }
+
+    #[test]
+    fn test_register_for_self_and_on_behalf() {
+        let mut service = CommandService::default();
+        register(
+            &mut service,
+            20,
+            r#"{"app":"com.example.notes","commands":[{"id":"new","title":"New Note"}]}"#,
+        );
+        assert_eq!(service.registry.count_of(20), 1);
+
+        // Only system processes may name the owner
+        let json = r#"{"pid":21,"commands":[{"id":"x","title":"X"}]}"#;
+        register(&mut service, 20, json);
+        assert_eq!(service.registry.count_of(21), 0);
+        register(&mut service, 1, json);
+        assert_eq!(service.registry.count_of(21), 1);
+        assert_eq!(service.registry.count_of(1), 0);
+    }
+
+    #[test]
+    fn test_unregister_own_commands_only() {
+        let mut service = CommandService::default();
+        register(&mut service, 20, r#"{"commands":[{"id":"a","title":"A"}]}"#);
+        register(&mut service, 21, r#"{"commands":[{"id":"a","title":"A"}]}"#);
+
+        let msg = mock_message(
+            commands_msg::MSG_COMMAND_UNREGISTER,
+            20,
+            br#"{"pid":21}"#.to_vec(),
+        );
+        service.handle_unregister(&msg).unwrap();
+        assert_eq!(service.registry.count_of(21), 1);
+
+        let msg = mock_message(commands_msg::MSG_COMMAND_UNREGISTER, 20, Vec::new());
+        service.handle_unregister(&msg).unwrap();
+        assert_eq!(service.registry.count_of(20), 0);
+    }
+
+    #[test]
+    fn test_invoke_requires_trusted_pid() {
+        assert!(CommandService::is_trusted(3));
+        assert!(!CommandService::is_trusted(20));
+
+        let request: InvokeRequest = serde_json::from_str(r#"{"pid":20,"id":"a"}"#).unwrap();
+        assert!(request.args.is_null());
+
+        // Handled without panicking whether or not the command exists
+        let mut service = CommandService::default();
+        register(&mut service, 20, r#"{"commands":[{"id":"a","title":"A"}]}"#);
+        for (from, id) in [(20, "a"), (3, "missing"), (3, "a")] {
+            let json = format!(r#"{{"pid":20,"id":"{}"}}"#, id);
+            let msg = mock_message(commands_msg::MSG_COMMAND_INVOKE, from, json.into_bytes());
+            service.handle_invoke(&msg).unwrap();
+        }
+    }
+}
@@ -0,0 +1,485 @@
+//! Command registry and palette matching
+//!
+//! A command belongs to the process that registered it and is identified by
+//! `(pid, id)`, so two apps may both offer `new-window`. Registering a
+//! command ID the process already has replaces it; a batch is validated as a
+//! whole and either registered entirely or not at all.
+//!
+//! Queries are fuzzy: each whitespace-separated term must appear, in order
+//! but not necessarily contiguously, in the command's title or in one of its
+//! keywords. Matches at the start of words and runs of consecutive
+//! characters score higher, and titles count more than keywords, so `nw`
+//! finds "New Window" ahead of a command merely tagged `network`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Maximum commands across all processes (DoS protection per Rule 11).
+pub const MAX_COMMANDS: usize = 512;
+
+/// Maximum commands registered by one process.
+pub const MAX_COMMANDS_PER_PID: usize = 32;
+
+/// Maximum command ID length in bytes.
+pub const MAX_ID_LEN: usize = 64;
+
+/// Maximum title or app name length in bytes.
+pub const MAX_TITLE_LEN: usize = 96;
+
+/// Maximum keywords per command.
+pub const MAX_KEYWORDS: usize = 8;
+
+/// Maximum keyword length in bytes.
+pub const MAX_KEYWORD_LEN: usize = 32;
+
+/// Maximum query length in bytes.
+pub const MAX_QUERY_LEN: usize = 128;
+
+/// Results returned when the query does not ask for a number.
+pub const DEFAULT_RESULTS: usize = 20;
+
+/// Upper bound on the results of one query.
+pub const MAX_RESULTS: usize = 100;
+
+/// A command as declared by its app.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandSpec {
+    /// Identifier passed back to the app on invocation
+    pub id: String,
+    /// What the palette shows
+    pub title: String,
+    /// Extra words the command is found by
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+impl CommandSpec {
+    fn is_valid(&self) -> bool {
+        let id_ok = !self.id.is_empty()
+            && self.id.len() <= MAX_ID_LEN
+            && self
+                .id
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+        let title_ok = !self.title.trim().is_empty() && self.title.len() <= MAX_TITLE_LEN;
+        let keywords_ok = self.keywords.len() <= MAX_KEYWORDS
+            && self
+                .keywords
+                .iter()
+                .all(|k| !k.trim().is_empty() && k.len() <= MAX_KEYWORD_LEN);
+        id_ok && title_ok && keywords_ok
+    }
+}
+
+/// A registered command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegisteredCommand {
+    /// Owning process
+    pub pid: u32,
+    /// Owning app's manifest ID (may be empty)
+    pub app: String,
+    pub spec: CommandSpec,
+}
+
+/// A query result.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CommandMatch {
+    pub pid: u32,
+    pub app: String,
+    pub id: String,
+    pub title: String,
+    /// Higher is better
+    pub score: u32,
+}
+
+/// Why a registry operation was refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    /// Empty, too long or malformed ID, title or keywords
+    InvalidCommand,
+    /// App name longer than [`MAX_TITLE_LEN`]
+    InvalidApp,
+    /// The same ID twice in one batch
+    DuplicateId,
+    /// Query longer than [`MAX_QUERY_LEN`]
+    InvalidQuery,
+    /// Per-process or total command limit reached
+    TooManyCommands,
+}
+
+impl RegistryError {
+    /// Message for error responses.
+    pub fn message(self) -> &'static str {
+        match self {
+            RegistryError::InvalidCommand => "Invalid command",
+            RegistryError::InvalidApp => "Invalid app name",
+            RegistryError::DuplicateId => "Duplicate command ID",
+            RegistryError::InvalidQuery => "Query too long",
+            RegistryError::TooManyCommands => "Too many commands",
+        }
+    }
+}
+
+/// Commands of every process, in registration order.
+#[derive(Clone, Debug, Default)]
+pub struct CommandRegistry {
+    commands: Vec<RegisteredCommand>,
+}
+
+impl CommandRegistry {
+    /// Register `specs` for `pid`, replacing commands with the same IDs.
+    ///
+    /// Returns the number registered.
+    pub fn register(
+        &mut self,
+        pid: u32,
+        app: &str,
+        specs: Vec<CommandSpec>,
+    ) -> Result<usize, RegistryError> {
+        if app.len() > MAX_TITLE_LEN {
+            return Err(RegistryError::InvalidApp);
+        }
+        if !specs.iter().all(CommandSpec::is_valid) {
+            return Err(RegistryError::InvalidCommand);
+        }
+        for (i, spec) in specs.iter().enumerate() {
+            if specs[..i].iter().any(|s| s.id == spec.id) {
+                return Err(RegistryError::DuplicateId);
+            }
+        }
+
+        let replaced = self
+            .commands
+            .iter()
+            .filter(|c| c.pid == pid && specs.iter().any(|s| s.id == c.spec.id))
+            .count();
+        let added = specs.len() - replaced;
+        if self.count_of(pid) + added > MAX_COMMANDS_PER_PID
+            || self.commands.len() + added > MAX_COMMANDS
+        {
+            return Err(RegistryError::TooManyCommands);
+        }
+
+        let count = specs.len();
+        for spec in specs {
+            let command = RegisteredCommand {
+                pid,
+                app: String::from(app),
+                spec,
+            };
+            match self
+                .commands
+                .iter_mut()
+                .find(|c| c.pid == pid && c.spec.id == command.spec.id)
+            {
+                Some(existing) => *existing = command,
+                None => self.commands.push(command),
+            }
+        }
+        Ok(count)
+    }
+
+    /// Drop `pid`'s command `id`, or all of its commands.
+    ///
+    /// Returns the number removed.
+    pub fn unregister(&mut self, pid: u32, id: Option<&str>) -> usize {
+        let before = self.commands.len();
+        self.commands
+            .retain(|c| c.pid != pid || id.is_some_and(|id| c.spec.id != id));
+        before - self.commands.len()
+    }
+
+    /// The command `id` of `pid`.
+    pub fn get(&self, pid: u32, id: &str) -> Option<&RegisteredCommand> {
+        self.commands
+            .iter()
+            .find(|c| c.pid == pid && c.spec.id == id)
+    }
+
+    /// Commands matching `query`, best first, at most `limit`.
+    ///
+    /// An empty query matches everything, ordered by title.
+    pub fn query(&self, query: &str, limit: usize) -> Result<Vec<CommandMatch>, RegistryError> {
+        if query.len() > MAX_QUERY_LEN {
+            return Err(RegistryError::InvalidQuery);
+        }
+        let terms: Vec<Vec<char>> = query
+            .split_whitespace()
+            .map(|t| t.chars().map(fold).collect())
+            .collect();
+
+        let mut matches: Vec<CommandMatch> = self
+            .commands
+            .iter()
+            .filter_map(|c| {
+                let score = score_command(&terms, &c.spec)?;
+                Some(CommandMatch {
+                    pid: c.pid,
+                    app: c.app.clone(),
+                    id: c.spec.id.clone(),
+                    title: c.spec.title.clone(),
+                    score,
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.title.cmp(&b.title)));
+        matches.truncate(limit.min(MAX_RESULTS));
+        Ok(matches)
+    }
+
+    /// Number of commands `pid` has registered.
+    pub fn count_of(&self, pid: u32) -> usize {
+        self.commands.iter().filter(|c| c.pid == pid).count()
+    }
+
+    /// Number of commands registered.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Whether no command is registered.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+/// Score of `spec` for the query `terms`, or `None` if a term is missing.
+fn score_command(terms: &[Vec<char>], spec: &CommandSpec) -> Option<u32> {
+    let mut total = 0;
+    for term in terms {
+        let title = fuzzy_score(term, &spec.title).map(|s| s * 2);
+        let keyword = spec
+            .keywords
+            .iter()
+            .filter_map(|k| fuzzy_score(term, k))
+            .max();
+        total += title.max(keyword)?;
+    }
+    Some(total)
+}
+
+/// Lowercase `c` for matching (first char of its lowercase form).
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Best score of folded `term` as a subsequence of `text`, or `None`.
+///
+/// Every matched character scores 1, plus 4 if it follows the previous
+/// match directly and 8 if it starts a word (after a non-alphanumeric
+/// character or a camelCase hump); a term the text starts with gets 16
+/// more. The best placement of the term is found, not just the first.
+pub fn fuzzy_score(term: &[char], text: &str) -> Option<u32> {
+    let Some((&first, rest)) = term.split_first() else {
+        return Some(0);
+    };
+    let text: Vec<char> = text.chars().collect();
+    let folded: Vec<char> = text.iter().copied().map(fold).collect();
+    let gain: Vec<u32> = (0..text.len())
+        .map(|i| {
+            let word_start = i == 0 || {
+                let (p, c) = (text[i - 1], text[i]);
+                !p.is_alphanumeric() || (p.is_lowercase() && c.is_uppercase())
+            };
+            if word_start {
+                9
+            } else {
+                1
+            }
+        })
+        .collect();
+
+    // best[i]: best score of the term so far with its last character at i
+    let mut best: Vec<Option<u32>> = (0..text.len())
+        .map(|i| (folded[i] == first).then_some(gain[i]))
+        .collect();
+    for &c in rest {
+        let mut next = alloc::vec![None; text.len()];
+        // Best over positions before i - 1 (no adjacency bonus)
+        let mut earlier: Option<u32> = None;
+        for i in 1..text.len() {
+            if i >= 2 {
+                earlier = earlier.max(best[i - 2]);
+            }
+            if folded[i] == c {
+                let from = earlier.max(best[i - 1].map(|s| s + 4));
+                next[i] = from.map(|s| s + gain[i]);
+            }
+        }
+        best = next;
+    }
+
+    let score = best.into_iter().flatten().max()?;
+    let prefix = folded.starts_with(term);
+    Some(if prefix { score + 16 } else { score })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn spec(id: &str, title: &str, keywords: &[&str]) -> CommandSpec {
+        CommandSpec {
+            id: String::from(id),
+            title: String::from(title),
+            keywords: keywords.iter().map(|k| String::from(*k)).collect(),
+        }
+    }
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score(&chars("xyz"), "New Window"), None);
+        assert_eq!(fuzzy_score(&chars("wz"), "New Window"), None);
+
+        // Word starts and runs beat scattered matches
+        let initials = fuzzy_score(&chars("nw"), "New Window").unwrap();
+        let scattered = fuzzy_score(&chars("nw"), "Unknown").unwrap();
+        assert!(initials > scattered);
+
+        // A prefix beats the same run later on
+        let prefix = fuzzy_score(&chars("new"), "New Window").unwrap();
+        let later = fuzzy_score(&chars("new"), "Open New").unwrap();
+        assert!(prefix > later);
+
+        // camelCase humps count as word starts
+        assert!(
+            fuzzy_score(&chars("ts"), "toggleSidebar").unwrap()
+                > fuzzy_score(&chars("ts"), "tabstops").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_register_replaces_and_validates() {
+        let mut registry = CommandRegistry::default();
+        let specs = vec![
+            spec("clear", "Clear", &[]),
+            spec("copy", "Copy Result", &["clipboard"]),
+        ];
+        assert_eq!(registry.register(10, "com.zero.calculator", specs), Ok(2));
+
+        assert_eq!(
+            registry.register(10, "", vec![spec("clear", "Clear All", &[])]),
+            Ok(1)
+        );
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get(10, "clear").unwrap().spec.title, "Clear All");
+
+        // The same ID for another process is a different command
+        registry
+            .register(11, "", vec![spec("clear", "Clear", &[])])
+            .unwrap();
+        assert_eq!(registry.len(), 3);
+
+        for bad in [
+            spec("", "Title", &[]),
+            spec("a b", "Title", &[]),
+            spec("a", " ", &[]),
+        ] {
+            assert_eq!(
+                registry.register(12, "", vec![bad]),
+                Err(RegistryError::InvalidCommand)
+            );
+        }
+        let dup = vec![spec("a", "A", &[]), spec("a", "B", &[])];
+        assert_eq!(
+            registry.register(12, "", dup),
+            Err(RegistryError::DuplicateId)
+        );
+        assert_eq!(registry.count_of(12), 0);
+    }
+
+    #[test]
+    fn test_register_limits_are_all_or_nothing() {
+        let mut registry = CommandRegistry::default();
+        let batch = |n: usize, offset: usize| {
+            (0..n)
+                .map(|i| spec(&alloc::format!("c{}", i + offset), "Command", &[]))
+                .collect::<Vec<_>>()
+        };
+        registry
+            .register(10, "", batch(MAX_COMMANDS_PER_PID - 1, 0))
+            .unwrap();
+        assert_eq!(
+            registry.register(10, "", batch(2, MAX_COMMANDS_PER_PID)),
+            Err(RegistryError::TooManyCommands)
+        );
+        assert_eq!(registry.count_of(10), MAX_COMMANDS_PER_PID - 1);
+
+        // Replacing does not count against the limit
+        registry
+            .register(10, "", batch(2, MAX_COMMANDS_PER_PID - 2))
+            .unwrap();
+        assert_eq!(registry.count_of(10), MAX_COMMANDS_PER_PID);
+    }
+
+    #[test]
+    fn test_unregister() {
+        let mut registry = CommandRegistry::default();
+        registry
+            .register(10, "", vec![spec("a", "A", &[]), spec("b", "B", &[])])
+            .unwrap();
+        registry
+            .register(11, "", vec![spec("a", "A", &[])])
+            .unwrap();
+
+        assert_eq!(registry.unregister(10, Some("a")), 1);
+        assert_eq!(registry.unregister(10, Some("a")), 0);
+        assert_eq!(registry.unregister(10, None), 1);
+        assert_eq!(registry.len(), 1);
+        assert!(registry.get(11, "a").is_some());
+    }
+
+    #[test]
+    fn test_query_ranks_and_limits() {
+        let mut registry = CommandRegistry::default();
+        registry
+            .register(
+                10,
+                "com.zero.terminal",
+                vec![
+                    spec("new-window", "New Window", &[]),
+                    spec("net", "Show Connections", &["network"]),
+                ],
+            )
+            .unwrap();
+        registry
+            .register(
+                11,
+                "com.zero.clock",
+                vec![spec("timer", "Start Timer", &["countdown"])],
+            )
+            .unwrap();
+
+        let results = registry.query("nw", 10).unwrap();
+        let ids: Vec<&str> = results.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["new-window", "net", "timer"]);
+        assert_eq!(registry.query("nw", 1).unwrap().len(), 1);
+
+        // Every term must match
+        let results = registry.query("start count", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            (results[0].pid, results[0].app.as_str()),
+            (11, "com.zero.clock")
+        );
+        assert!(registry.query("start zebra", 10).unwrap().is_empty());
+
+        // Empty query lists everything by title
+        let titles: Vec<_> = registry
+            .query("  ", 2)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.title)
+            .collect();
+        assert_eq!(titles, ["New Window", "Show Connections"]);
+
+        let long = "x".repeat(MAX_QUERY_LEN + 1);
+        assert_eq!(registry.query(&long, 10), Err(RegistryError::InvalidQuery));
+    }
+}
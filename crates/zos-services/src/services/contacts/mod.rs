@@ -0,0 +1,939 @@
+//! Contacts Service
+//!
+//! The AddressBookService keeps the people a user shares files with and
+//! messages, so sharing and messaging features look them up in one place.
+//! It:
+//! - Stores contacts via VFS (see [`store`])
+//! - Verifies a contact's identity key by its fingerprint, read out by the
+//!   contact over another channel
+//! - Exports and imports contacts as cards
+//! - Builds the user's own card from the public keys IdentityService holds
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - ADD/UPDATE/DELETE/VERIFY: Contact validated AND stored in memory AND a
+//!   write of the contact store started
+//! - LIST/EXPORT: Contacts read from the store
+//! - IMPORT: Cards parsed AND valid cards merged by identity key AND a write
+//!   of the contact store started
+//! - MY_CARD: Keys returned by IdentityService for the user
+//!
+//! **Acceptable partial failure:**
+//! - Contact store write fails → changes stay in memory until the service restarts
+//! - Cards imported with parts left out, each reported as a warning
+//!
+//! **Forbidden:**
+//! - Changing contacts before the store has loaded (the write would replace it)
+//! - Keeping a contact verified after its identity key changed
+//! - Unbounded contact, endpoint or pending request growth (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_CONTACT_ADD (0x8800)`: Add a contact
+//! - `MSG_CONTACT_UPDATE (0x8802)`: Replace a contact's fields
+//! - `MSG_CONTACT_DELETE (0x8804)`: Delete a contact
+//! - `MSG_CONTACT_LIST (0x8806)`: List contacts, optionally by name
+//! - `MSG_CONTACT_VERIFY (0x8808)`: Confirm a contact's identity key fingerprint
+//! - `MSG_CONTACT_EXPORT (0x880A)`: Export contacts as cards
+//! - `MSG_CONTACT_IMPORT (0x880C)`: Import cards from a VFS file or inline
+//! - `MSG_CONTACT_MY_CARD (0x880E)`: The card this user shares
+//!
+//! # Identity
+//!
+//! The user's own card is built from `MSG_GET_KEY_FINGERPRINT`, sent to the
+//! IdentityService by name. Its responses come back through the supervisor,
+//! which routes the IdentityService's replies to this service via Init.
+//! They carry no request ID, so they answer card requests oldest first.
+//!
+//! # Storage Access
+//!
+//! This service uses VFS IPC (async/await pattern, see
+//! [`zos_vfs::client::futures`]) to persist contacts and read cards to
+//! import. All storage operations flow through the VFS service per Invariant 31.
+
+extern crate alloc;
+
+pub mod store;
+
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+use zos_identity::ipc::{GetKeyFingerprintRequest, GetKeyFingerprintResponse};
+use zos_identity::serde_helpers::u128_hex_string;
+use zos_process::identity_query;
+use zos_vfs::client::futures::VfsExecutor;
+
+use crate::manifests::CONTACTS_MANIFEST;
+use crate::response::JsonResponder;
+
+pub use store::{
+    Contact, ContactBundle, ContactCard, ContactFields, ContactStore, Endpoint, EndpointKind,
+    CARD_VERSION, CONTACTS_PATH,
+};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for contacts service - re-exported from zos-ipc.
+pub mod contacts_msg {
+    pub use zos_ipc::contacts::*;
+}
+
+/// Contact saves and card imports in flight at once (Rule 11)
+const MAX_VFS_TASKS: usize = 32;
+
+/// Maximum card requests awaiting IdentityService (DoS protection per Rule 11)
+const MAX_PENDING_CARDS: usize = 8;
+
+// =============================================================================
+// Request Types
+// =============================================================================
+
+/// Payload of MSG_CONTACT_UPDATE.
+#[derive(Clone, Debug, Deserialize)]
+struct UpdateRequest {
+    id: u32,
+    #[serde(flatten)]
+    fields: ContactFields,
+}
+
+/// Payload of MSG_CONTACT_DELETE.
+#[derive(Clone, Debug, Deserialize)]
+struct DeleteRequest {
+    id: u32,
+}
+
+/// Payload of MSG_CONTACT_LIST.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct ListRequest {
+    #[serde(default)]
+    search: Option<String>,
+}
+
+/// Payload of MSG_CONTACT_LIST_RESPONSE.
+#[derive(Clone, Debug, Serialize)]
+struct ListResponse {
+    contacts: Vec<Contact>,
+}
+
+/// Payload of MSG_CONTACT_VERIFY.
+#[derive(Clone, Debug, Deserialize)]
+struct VerifyRequest {
+    id: u32,
+    fingerprint: String,
+}
+
+/// Payload of MSG_CONTACT_EXPORT.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct ExportRequest {
+    #[serde(default)]
+    ids: Option<Vec<u32>>,
+}
+
+/// Payload of MSG_CONTACT_IMPORT: a VFS path or the cards themselves.
+#[derive(Clone, Debug, Deserialize)]
+struct ImportRequest {
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    cards: Option<Vec<ContactCard>>,
+}
+
+/// Payload of MSG_CONTACT_IMPORT_RESPONSE.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+struct ImportResponse {
+    imported: u32,
+    updated: u32,
+    skipped: u32,
+    warnings: Vec<String>,
+}
+
+/// Payload of MSG_CONTACT_MY_CARD.
+#[derive(Clone, Debug, Deserialize)]
+struct MyCardRequest {
+    #[serde(with = "u128_hex_string")]
+    user_id: u128,
+    name: String,
+    #[serde(default)]
+    endpoints: Vec<Endpoint>,
+}
+
+// =============================================================================
+// Pending Operations
+// =============================================================================
+
+/// A finished VFS task.
+enum Done {
+    /// The contact store read on startup
+    Loaded(Result<Vec<u8>, String>),
+    /// A write of the contact store after a change
+    Saved(Result<(), String>),
+    /// Exported cards read to import
+    ImportRead {
+        client_pid: u32,
+        cap_slots: Vec<u32>,
+        path: String,
+        result: Result<Vec<u8>, String>,
+    },
+}
+
+/// A card request waiting on the user's keys from IdentityService.
+#[derive(Clone)]
+struct PendingCard {
+    client_pid: u32,
+    cap_slots: Vec<u32>,
+    name: String,
+    endpoints: Vec<Endpoint>,
+}
+
+// =============================================================================
+// AddressBookService Application
+// =============================================================================
+
+/// AddressBookService - contacts with verified keys for sharing and messaging
+pub struct AddressBookService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// All contacts
+    store: ContactStore,
+    /// Whether the contact store has been loaded; until then it is neither
+    /// changed nor saved
+    loaded: bool,
+    /// VFS reads and writes in progress
+    vfs: VfsExecutor<Done>,
+    /// Card requests awaiting IdentityService, oldest first
+    pending_cards: VecDeque<PendingCard>,
+}
+
+impl Default for AddressBookService {
+    fn default() -> Self {
+        Self {
+            registered: false,
+            store: ContactStore::default(),
+            loaded: false,
+            vfs: VfsExecutor::new(),
+            pending_cards: VecDeque::new(),
+        }
+    }
+}
+
+impl AddressBookService {
+    /// Whether another save or import may start.
+    fn check_task_limit(&self) -> bool {
+        if self.vfs.running() >= MAX_VFS_TASKS {
+            syscall::debug(&format!(
+                "AddressBookService: VFS task limit reached ({}/{})",
+                self.vfs.running(),
+                MAX_VFS_TASKS
+            ));
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Start a write of the contact store after a change.
+    fn contacts_changed(&mut self) {
+        if !self.check_task_limit() {
+            return;
+        }
+        let value = self.store.to_json();
+        let vfs = self.vfs.handle();
+        self.vfs
+            .spawn(async move { Done::Saved(vfs.write(CONTACTS_PATH, &value).await) });
+        // Fails right away if the VFS can't be reached
+        self.finish_tasks();
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Refuse changes until the store has loaded. Returns true if refused.
+    fn refuse_until_loaded(&self, msg: &Message, tag: u32) -> Result<bool, AppError> {
+        if self.loaded {
+            return Ok(false);
+        }
+        self.send_error_response(
+            msg.from_pid,
+            &msg.cap_slots,
+            tag,
+            "Service busy: contacts are loading",
+        )?;
+        Ok(true)
+    }
+
+    /// Handle MSG_CONTACT_ADD
+    fn handle_add(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = contacts_msg::MSG_CONTACT_ADD_RESPONSE;
+        if self.refuse_until_loaded(msg, tag)? {
+            return Ok(());
+        }
+        let fields: ContactFields = match serde_json::from_slice(&msg.data) {
+            Ok(f) => f,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid contact: expected {\"name\": string}",
+                );
+            }
+        };
+
+        match self.store.add(fields) {
+            Ok(contact) => {
+                syscall::debug(&format!(
+                    "AddressBookService: PID {} added contact {}",
+                    msg.from_pid, contact.id
+                ));
+                self.contacts_changed();
+                self.send_contact_response(msg, tag, &contact)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_CONTACT_UPDATE
+    fn handle_update(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = contacts_msg::MSG_CONTACT_UPDATE_RESPONSE;
+        if self.refuse_until_loaded(msg, tag)? {
+            return Ok(());
+        }
+        let request: UpdateRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid update: expected {\"id\": u32, \"name\": string}",
+                );
+            }
+        };
+
+        match self.store.update(request.id, request.fields) {
+            Ok(contact) => {
+                self.contacts_changed();
+                self.send_contact_response(msg, tag, &contact)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_CONTACT_DELETE
+    fn handle_delete(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = contacts_msg::MSG_CONTACT_DELETE_RESPONSE;
+        if self.refuse_until_loaded(msg, tag)? {
+            return Ok(());
+        }
+        let request: DeleteRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid delete: expected {\"id\": u32}",
+                );
+            }
+        };
+
+        match self.store.delete(request.id) {
+            Ok(contact) => {
+                self.contacts_changed();
+                self.send_contact_response(msg, tag, &contact)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_CONTACT_LIST
+    fn handle_list(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = contacts_msg::MSG_CONTACT_LIST_RESPONSE;
+        let request: ListRequest = if msg.data.is_empty() {
+            ListRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid list: expected {\"search\": string?}",
+                    );
+                }
+            }
+        };
+
+        let contacts = self.store.list(request.search.as_deref());
+        let json = serde_json::to_vec(&ListResponse { contacts }).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_CONTACT_VERIFY
+    fn handle_verify(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let tag = contacts_msg::MSG_CONTACT_VERIFY_RESPONSE;
+        if self.refuse_until_loaded(msg, tag)? {
+            return Ok(());
+        }
+        let request: VerifyRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid verify: expected {\"id\": u32, \"fingerprint\": string}",
+                );
+            }
+        };
+
+        match self.store.verify(request.id, &request.fingerprint, now_ms) {
+            Ok(contact) => {
+                syscall::debug(&format!(
+                    "AddressBookService: PID {} verified contact {}",
+                    msg.from_pid, contact.id
+                ));
+                self.contacts_changed();
+                self.send_contact_response(msg, tag, &contact)
+            }
+            Err(e) => {
+                // Rule 10: a failed verification may be an impersonation
+                syscall::debug(&format!(
+                    "AddressBookService: Verification of contact {} failed: {}",
+                    request.id, e
+                ));
+                self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e)
+            }
+        }
+    }
+
+    /// Handle MSG_CONTACT_EXPORT
+    fn handle_export(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = contacts_msg::MSG_CONTACT_EXPORT_RESPONSE;
+        let request: ExportRequest = if msg.data.is_empty() {
+            ExportRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid export: expected {\"ids\": [u32]?}",
+                    );
+                }
+            }
+        };
+
+        match self.store.export(request.ids.as_deref()) {
+            Ok(cards) => {
+                let bundle = ContactBundle {
+                    version: CARD_VERSION,
+                    cards,
+                };
+                let json = serde_json::to_vec(&bundle).unwrap_or_default();
+                self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_CONTACT_IMPORT
+    fn handle_import(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = contacts_msg::MSG_CONTACT_IMPORT_RESPONSE;
+        if self.refuse_until_loaded(msg, tag)? {
+            return Ok(());
+        }
+        let request: ImportRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid import: expected {\"path\": string} or {\"cards\": [card]}",
+                );
+            }
+        };
+
+        match (request.path, request.cards) {
+            (None, Some(cards)) => {
+                let result = self.import(cards);
+                self.send_import_response(msg.from_pid, &msg.cap_slots, result)
+            }
+            (Some(path), None) => {
+                if !self.check_task_limit() {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Service busy: VFS task limit reached",
+                    );
+                }
+                syscall::debug(&format!(
+                    "AddressBookService: PID {} imports {}",
+                    msg.from_pid, path
+                ));
+                let vfs = self.vfs.handle();
+                let client_pid = msg.from_pid;
+                let cap_slots = msg.cap_slots.clone();
+                self.vfs.spawn(async move {
+                    let result = vfs.read(&path).await;
+                    Done::ImportRead {
+                        client_pid,
+                        cap_slots,
+                        path,
+                        result,
+                    }
+                });
+                self.finish_tasks();
+                Ok(())
+            }
+            _ => self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Invalid import: give exactly one of \"path\" and \"cards\"",
+            ),
+        }
+    }
+
+    /// Merge imported cards into the store.
+    fn import(&mut self, cards: Vec<ContactCard>) -> Result<ImportResponse, String> {
+        let total = cards.len() as u32;
+        let (counts, warnings) = self.store.merge(cards);
+        let skipped = total - counts.imported - counts.updated;
+        syscall::debug(&format!(
+            "AddressBookService: Imported {} contacts, updated {}, skipped {}",
+            counts.imported, counts.updated, skipped
+        ));
+        if counts.imported + counts.updated > 0 {
+            self.contacts_changed();
+        }
+        Ok(ImportResponse {
+            imported: counts.imported,
+            updated: counts.updated,
+            skipped,
+            warnings,
+        })
+    }
+
+    /// Handle MSG_CONTACT_MY_CARD: ask IdentityService for the user's keys.
+    fn handle_my_card(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = contacts_msg::MSG_CONTACT_MY_CARD_RESPONSE;
+        let request: MyCardRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid card request: expected {\"user_id\": hex, \"name\": string}",
+                );
+            }
+        };
+        if self.pending_cards.len() >= MAX_PENDING_CARDS {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Service busy: too many card requests",
+            );
+        }
+        // Validate the card's fields before asking for keys
+        let fields = ContactFields {
+            name: request.name,
+            identity_key: None,
+            encryption_key: None,
+            endpoints: request.endpoints,
+            notes: String::new(),
+        };
+        let fields = match fields.validate() {
+            Ok(f) => f,
+            Err(e) => return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        };
+
+        let identity_request = GetKeyFingerprintRequest {
+            user_id: request.user_id,
+        };
+        let json = serde_json::to_vec(&identity_request).unwrap_or_default();
+        if let Err(e) =
+            syscall::send_named("identity", identity_query::MSG_GET_KEY_FINGERPRINT, &json)
+        {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                &format!("IdentityService unreachable: {}", e),
+            );
+        }
+        self.pending_cards.push_back(PendingCard {
+            client_pid: msg.from_pid,
+            cap_slots: msg.cap_slots.clone(),
+            name: fields.name,
+            endpoints: fields.endpoints,
+        });
+        Ok(())
+    }
+
+    /// Handle MSG_GET_KEY_FINGERPRINT_RESPONSE: answer the oldest card request.
+    fn handle_key_fingerprint(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Some(pending) = self.pending_cards.pop_front() else {
+            syscall::debug("AddressBookService: Key fingerprint but no pending card request");
+            return Ok(());
+        };
+        let tag = contacts_msg::MSG_CONTACT_MY_CARD_RESPONSE;
+        let response: GetKeyFingerprintResponse = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    pending.client_pid,
+                    &pending.cap_slots,
+                    tag,
+                    &format!(
+                        "IdentityService refused: {}",
+                        String::from_utf8_lossy(&msg.data)
+                    ),
+                );
+            }
+        };
+        match response.result {
+            Ok(keys) => {
+                let card = ContactCard {
+                    name: pending.name,
+                    identity_key: Some(keys.identity_signing_pub_key),
+                    encryption_key: Some(keys.machine_encryption_pub_key),
+                    endpoints: pending.endpoints,
+                    fingerprint: Some(keys.fingerprint),
+                };
+                let json = serde_json::to_vec(&card).unwrap_or_default();
+                self.send_response(pending.client_pid, &pending.cap_slots, tag, &json)
+            }
+            Err(e) => self.send_error_response(
+                pending.client_pid,
+                &pending.cap_slots,
+                tag,
+                &format!("No identity keys: {:?}", e),
+            ),
+        }
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Handle a VFS response to one of our tasks. Returns false if the
+    /// message isn't one.
+    fn handle_vfs_response(&mut self, msg: &Message) -> bool {
+        if !self.vfs.on_message(msg.tag, &msg.data) {
+            return false;
+        }
+        self.finish_tasks();
+        true
+    }
+
+    /// Handle the outcome of every finished VFS task.
+    fn finish_tasks(&mut self) {
+        while let Some(done) = self.vfs.next_finished() {
+            match done {
+                Done::Loaded(result) => {
+                    match result.ok().and_then(|data| ContactStore::from_json(&data)) {
+                        Some(stored) => {
+                            syscall::debug(&format!(
+                                "AddressBookService: Loaded {} contacts",
+                                stored.len()
+                            ));
+                            self.store = stored;
+                        }
+                        None => syscall::debug("AddressBookService: No stored contacts found"),
+                    }
+                    self.loaded = true;
+                }
+
+                Done::Saved(result) => {
+                    if let Err(e) = result {
+                        syscall::debug(&format!(
+                            "AddressBookService: Failed to write {}: {}",
+                            CONTACTS_PATH, e
+                        ));
+                    }
+                }
+
+                Done::ImportRead {
+                    client_pid,
+                    cap_slots,
+                    path,
+                    result,
+                } => {
+                    let result = match result {
+                        Ok(data) => match serde_json::from_slice::<ContactBundle>(&data) {
+                            Ok(bundle) if bundle.version == CARD_VERSION => {
+                                self.import(bundle.cards)
+                            }
+                            Ok(bundle) => Err(format!(
+                                "{} has unsupported card version {}",
+                                path, bundle.version
+                            )),
+                            Err(_) => Err(format!("{} is not an exported contacts file", path)),
+                        },
+                        // Rule 9: Include operation context in error
+                        Err(e) => Err(format!("VFS read failed for {}: {}", path, e)),
+                    };
+                    if let Err(e) = self.send_import_response(client_pid, &cap_slots, result) {
+                        syscall::debug(&format!(
+                            "AddressBookService: Failed to answer import from PID {}: {:?}",
+                            client_pid, e
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // =========================================================================
+    // Response helpers
+    // =========================================================================
+
+    fn send_contact_response(
+        &self,
+        msg: &Message,
+        tag: u32,
+        contact: &Contact,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_vec(contact).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    fn send_import_response(
+        &self,
+        to_pid: u32,
+        cap_slots: &[u32],
+        result: Result<ImportResponse, String>,
+    ) -> Result<(), AppError> {
+        let tag = contacts_msg::MSG_CONTACT_IMPORT_RESPONSE;
+        match result {
+            Ok(response) => {
+                let json = serde_json::to_vec(&response).unwrap_or_default();
+                self.send_response(to_pid, cap_slots, tag, &json)
+            }
+            Err(e) => self.send_error_response(to_pid, cap_slots, tag, &e),
+        }
+    }
+}
+
+impl JsonResponder for AddressBookService {
+    const SERVICE_NAME: &'static str = "AddressBookService";
+}
+
+impl ZeroApp for AddressBookService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &CONTACTS_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("AddressBookService starting (PID {})", ctx.pid));
+
+        // Register with init as "contacts" service
+        let service_name = "contacts";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("AddressBookService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+
+        // Load contacts via VFS
+        let vfs = self.vfs.handle();
+        self.vfs
+            .spawn(async move { Done::Loaded(vfs.read(CONTACTS_PATH).await) });
+        self.finish_tasks();
+
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        // VFS responses (Invariant 31 compliant - storage via VFS IPC)
+        if self.handle_vfs_response(&msg) {
+            return Ok(());
+        }
+
+        match msg.tag {
+            // IdentityService replies
+            identity_query::MSG_GET_KEY_FINGERPRINT_RESPONSE => self.handle_key_fingerprint(&msg),
+
+            // Contacts service protocol
+            contacts_msg::MSG_CONTACT_ADD => self.handle_add(&msg),
+            contacts_msg::MSG_CONTACT_UPDATE => self.handle_update(&msg),
+            contacts_msg::MSG_CONTACT_DELETE => self.handle_delete(&msg),
+            contacts_msg::MSG_CONTACT_LIST => self.handle_list(&msg),
+            contacts_msg::MSG_CONTACT_VERIFY => self.handle_verify(&msg, ctx.wallclock_ms),
+            contacts_msg::MSG_CONTACT_EXPORT => self.handle_export(&msg),
+            contacts_msg::MSG_CONTACT_IMPORT => self.handle_import(&msg),
+            contacts_msg::MSG_CONTACT_MY_CARD => self.handle_my_card(&msg),
+
+            _ => {
+                syscall::debug(&format!(
+                    "AddressBookService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("AddressBookService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+
+    const KEY: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+
+    fn loaded() -> AddressBookService {
+        AddressBookService {
+            loaded: true,
+            ..Default::default()
+        }
+    }
+
+    fn add(service: &mut AddressBookService, json: &str) {
+        let msg = mock_message(contacts_msg::MSG_CONTACT_ADD, 7, json.as_bytes().to_vec());
+        service.handle_add(&msg).unwrap();
+    }
+
+    #[test]
+    fn test_changes_refused_until_loaded() {
+        let mut service = AddressBookService::default();
+        add(&mut service, r#"{"name":"Alice"}"#);
+        assert!(service.store.is_empty());
+        assert_eq!(service.vfs.running(), 0);
+
+        let mut service = loaded();
+        add(&mut service, r#"{"name":"Alice"}"#);
+        assert_eq!(service.store.len(), 1);
+        // A write of the store was started
+        assert_eq!(service.vfs.running(), 1);
+    }
+
+    #[test]
+    fn test_verify_request() {
+        let mut service = loaded();
+        add(
+            &mut service,
+            &format!(r#"{{"name":"Alice","identity_key":"{}"}}"#, KEY),
+        );
+        let contact = service.store.list(None).remove(0);
+        let fingerprint = contact.fingerprint().unwrap();
+
+        let json = format!(r#"{{"id":{},"fingerprint":"0000"}}"#, contact.id);
+        let msg = mock_message(contacts_msg::MSG_CONTACT_VERIFY, 7, json.into_bytes());
+        service.handle_verify(&msg, 9).unwrap();
+        assert!(!service.store.get(contact.id).unwrap().verified);
+
+        let json = format!(r#"{{"id":{},"fingerprint":"{}"}}"#, contact.id, fingerprint);
+        let msg = mock_message(contacts_msg::MSG_CONTACT_VERIFY, 7, json.into_bytes());
+        service.handle_verify(&msg, 9).unwrap();
+        assert!(service.store.get(contact.id).unwrap().verified);
+    }
+
+    #[test]
+    fn test_import_needs_one_source() {
+        let mut service = loaded();
+        let msg = mock_message(
+            contacts_msg::MSG_CONTACT_IMPORT,
+            7,
+            br#"{"path":"/home/a.json","cards":[]}"#.to_vec(),
+        );
+        service.handle_import(&msg).unwrap();
+        assert_eq!(service.vfs.running(), 0);
+
+        let msg = mock_message(
+            contacts_msg::MSG_CONTACT_IMPORT,
+            7,
+            br#"{"path":"/home/a.json"}"#.to_vec(),
+        );
+        service.handle_import(&msg).unwrap();
+        assert_eq!(service.vfs.running(), 1);
+
+        let cards = br#"{"cards":[{"name":"Bob"},{"name":""}]}"#.to_vec();
+        let msg = mock_message(contacts_msg::MSG_CONTACT_IMPORT, 7, cards);
+        service.handle_import(&msg).unwrap();
+        assert_eq!(service.store.len(), 1);
+    }
+
+    #[test]
+    fn test_import_from_file() {
+        let mut service = loaded();
+        let msg = mock_message(
+            contacts_msg::MSG_CONTACT_IMPORT,
+            7,
+            br#"{"path":"/home/a.json"}"#.to_vec(),
+        );
+        service.handle_import(&msg).unwrap();
+
+        let bundle = format!(
+            r#"{{"version":{},"cards":[{{"name":"Bob"}}]}}"#,
+            CARD_VERSION
+        );
+        let content = serde_json::json!({ "result": { "Ok": bundle.as_bytes() } });
+        let read = mock_message(
+            zos_vfs::ipc::vfs_msg::MSG_VFS_READ_RESPONSE,
+            4,
+            serde_json::to_vec(&content).unwrap(),
+        );
+        assert!(service.handle_vfs_response(&read));
+        assert_eq!(service.store.len(), 1);
+        // The import read finished and a write of the store started
+        assert_eq!(service.vfs.running(), 1);
+    }
+
+    #[test]
+    fn test_my_card_answered_in_order() {
+        let mut service = AddressBookService::default();
+        for name in ["Me", "Me too"] {
+            let json = format!(r#"{{"user_id":"0x01","name":"{}"}}"#, name);
+            let msg = mock_message(contacts_msg::MSG_CONTACT_MY_CARD, 7, json.into_bytes());
+            service.handle_my_card(&msg).unwrap();
+        }
+        assert_eq!(service.pending_cards.len(), 2);
+
+        let keys = br#"{"result":{"Err":"KeysNotFound"}}"#.to_vec();
+        let msg = mock_message(identity_query::MSG_GET_KEY_FINGERPRINT_RESPONSE, 3, keys);
+        service.handle_key_fingerprint(&msg).unwrap();
+        assert_eq!(service.pending_cards.len(), 1);
+        assert_eq!(service.pending_cards[0].name, "Me too");
+    }
+}
@@ -0,0 +1,651 @@
+//! Contact store
+//!
+//! Contacts live in `/system/settings/contacts.json`. A contact is a name,
+//! up to two public keys and the endpoints the contact's machines can be
+//! reached through:
+//!
+//! - `identity_key`: the contact's identity signing key (Ed25519), which
+//!   names them across machines; its fingerprint is what users compare
+//! - `encryption_key`: the key messages to the contact are encrypted to
+//!   (X25519)
+//!
+//! Keys are hex strings of 32 bytes. A contact is *verified* once the user
+//! has confirmed the identity key's fingerprint with the contact over
+//! another channel; changing that key clears it.
+//!
+//! Contacts are shared as cards: the name, keys and endpoints, without the
+//! local notes or verification. Imported cards are merged by identity key.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zos_identity::fingerprint::{fingerprint_matches, key_fingerprint};
+
+/// Storage path for contacts
+pub const CONTACTS_PATH: &str = "/system/settings/contacts.json";
+
+/// Maximum contacts stored (Rule 11: resource limits)
+pub const MAX_CONTACTS: usize = 1024;
+
+/// Maximum name length in bytes
+pub const MAX_NAME_LEN: usize = 256;
+
+/// Maximum notes length in bytes
+pub const MAX_NOTES_LEN: usize = 4096;
+
+/// Maximum endpoints per contact
+pub const MAX_ENDPOINTS: usize = 8;
+
+/// Maximum endpoint address length in bytes
+pub const MAX_ADDRESS_LEN: usize = 512;
+
+/// Version of the exported card format
+pub const CARD_VERSION: u32 = 1;
+
+/// How a contact's machines can be reached for sync and messaging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointKind {
+    /// A WebSocket relay the contact's machines connect to (`wss://` URL)
+    Relay,
+    /// One of the contact's enrolled machines, by machine ID (hex)
+    Machine,
+}
+
+/// An endpoint of a contact.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Endpoint {
+    pub kind: EndpointKind,
+    pub address: String,
+    /// What the user calls it (e.g. "laptop")
+    #[serde(default)]
+    pub label: String,
+}
+
+impl Endpoint {
+    fn validate(&self) -> Result<(), String> {
+        if self.address.is_empty() {
+            return Err(String::from("Endpoint address is required"));
+        }
+        if self.address.len() > MAX_ADDRESS_LEN || self.label.len() > MAX_NAME_LEN {
+            return Err(format!("Endpoint too long (max {} bytes)", MAX_ADDRESS_LEN));
+        }
+        match self.kind {
+            EndpointKind::Relay if !self.address.starts_with("wss://") => Err(format!(
+                "Relay endpoints must be wss:// URLs: {}",
+                self.address
+            )),
+            EndpointKind::Machine
+                if !self
+                    .address
+                    .trim_start_matches("0x")
+                    .chars()
+                    .all(|c| c.is_ascii_hexdigit()) =>
+            {
+                Err(format!(
+                    "Machine endpoints must be hex machine IDs: {}",
+                    self.address
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A stored contact.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    pub id: u32,
+    #[serde(flatten)]
+    pub fields: ContactFields,
+    /// Whether the user confirmed the identity key's fingerprint
+    #[serde(default)]
+    pub verified: bool,
+    /// When it was confirmed, ms since the Unix epoch
+    #[serde(default)]
+    pub verified_at_ms: Option<u64>,
+}
+
+impl Contact {
+    /// Fingerprint of the identity key, if the contact has one.
+    pub fn fingerprint(&self) -> Option<String> {
+        self.fields
+            .identity_key
+            .as_deref()
+            .and_then(parse_key)
+            .map(|key| key_fingerprint(&key))
+    }
+
+    /// The contact as a card to share.
+    pub fn to_card(&self) -> ContactCard {
+        ContactCard {
+            name: self.fields.name.clone(),
+            identity_key: self.fields.identity_key.clone(),
+            encryption_key: self.fields.encryption_key.clone(),
+            endpoints: self.fields.endpoints.clone(),
+            fingerprint: self.fingerprint(),
+        }
+    }
+}
+
+/// The fields of a contact that clients set.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactFields {
+    pub name: String,
+    /// Identity signing public key (Ed25519, hex)
+    #[serde(default)]
+    pub identity_key: Option<String>,
+    /// Encryption public key (X25519, hex)
+    #[serde(default)]
+    pub encryption_key: Option<String>,
+    #[serde(default)]
+    pub endpoints: Vec<Endpoint>,
+    /// Private notes, never exported
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl ContactFields {
+    /// Validate, trimming the name and writing keys in lowercase.
+    pub fn validate(mut self) -> Result<Self, String> {
+        self.name = String::from(self.name.trim());
+        if self.name.is_empty() {
+            return Err(String::from("Name is required"));
+        }
+        if self.name.len() > MAX_NAME_LEN {
+            return Err(format!("Name too long (max {} bytes)", MAX_NAME_LEN));
+        }
+        if self.notes.len() > MAX_NOTES_LEN {
+            return Err(format!("Notes too long (max {} bytes)", MAX_NOTES_LEN));
+        }
+        for key in [&mut self.identity_key, &mut self.encryption_key]
+            .into_iter()
+            .flatten()
+        {
+            if parse_key(key).is_none() {
+                return Err(format!("Invalid key (expected 64 hex digits): {}", key));
+            }
+            *key = key.to_ascii_lowercase();
+        }
+        if self.endpoints.len() > MAX_ENDPOINTS {
+            return Err(format!("Too many endpoints (max {})", MAX_ENDPOINTS));
+        }
+        for endpoint in &self.endpoints {
+            endpoint.validate()?;
+        }
+        Ok(self)
+    }
+}
+
+/// A contact as shared with others.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactCard {
+    pub name: String,
+    #[serde(default)]
+    pub identity_key: Option<String>,
+    #[serde(default)]
+    pub encryption_key: Option<String>,
+    #[serde(default)]
+    pub endpoints: Vec<Endpoint>,
+    /// Fingerprint of the identity key, for display; checked on import
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+}
+
+/// Exported cards, as written to a file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ContactBundle {
+    pub version: u32,
+    pub cards: Vec<ContactCard>,
+}
+
+/// Decode a 32-byte public key from hex.
+pub fn parse_key(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut key = [0u8; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(key)
+}
+
+/// Outcome of an import.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MergeCounts {
+    pub imported: u32,
+    pub updated: u32,
+}
+
+/// All stored contacts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContactStore {
+    contacts: BTreeMap<u32, Contact>,
+    next_id: u32,
+}
+
+/// Stored form of [`ContactStore`]
+#[derive(Default, Serialize, Deserialize)]
+struct SavedContacts {
+    #[serde(default)]
+    contacts: Vec<Contact>,
+}
+
+impl ContactStore {
+    /// Serialize to JSON bytes
+    pub fn to_json(&self) -> Vec<u8> {
+        let saved = SavedContacts {
+            contacts: self.contacts.values().cloned().collect(),
+        };
+        serde_json::to_vec(&saved).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        let saved: SavedContacts = serde_json::from_slice(data).ok()?;
+        let mut store = Self::default();
+        for contact in saved.contacts.into_iter().take(MAX_CONTACTS) {
+            store.next_id = store.next_id.max(contact.id.wrapping_add(1));
+            store.contacts.insert(contact.id, contact);
+        }
+        Some(store)
+    }
+
+    pub fn len(&self) -> usize {
+        self.contacts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contacts.is_empty()
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Contact> {
+        self.contacts.get(&id)
+    }
+
+    /// The contact other than `except` holding identity key `key`.
+    fn find_by_key(&self, key: &str, except: Option<u32>) -> Option<&Contact> {
+        self.contacts
+            .values()
+            .find(|c| Some(c.id) != except && c.fields.identity_key.as_deref() == Some(key))
+    }
+
+    /// Refuse an identity key another contact already has.
+    fn check_key_unique(&self, fields: &ContactFields, except: Option<u32>) -> Result<(), String> {
+        match fields
+            .identity_key
+            .as_deref()
+            .and_then(|key| self.find_by_key(key, except))
+        {
+            Some(other) => Err(format!(
+                "Identity key already belongs to {}",
+                other.fields.name
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Store a new contact.
+    pub fn add(&mut self, fields: ContactFields) -> Result<Contact, String> {
+        let fields = fields.validate()?;
+        if self.contacts.len() >= MAX_CONTACTS {
+            return Err(format!("Contact limit reached (max {})", MAX_CONTACTS));
+        }
+        self.check_key_unique(&fields, None)?;
+        let contact = Contact {
+            id: self.alloc_id(),
+            fields,
+            verified: false,
+            verified_at_ms: None,
+        };
+        self.contacts.insert(contact.id, contact.clone());
+        Ok(contact)
+    }
+
+    /// Replace the fields of contact `id`. A different identity key clears
+    /// its verification.
+    pub fn update(&mut self, id: u32, fields: ContactFields) -> Result<Contact, String> {
+        let fields = fields.validate()?;
+        self.check_key_unique(&fields, Some(id))?;
+        let contact = self
+            .contacts
+            .get_mut(&id)
+            .ok_or_else(|| format!("No contact {}", id))?;
+        if contact.fields.identity_key != fields.identity_key {
+            contact.verified = false;
+            contact.verified_at_ms = None;
+        }
+        contact.fields = fields;
+        Ok(contact.clone())
+    }
+
+    /// Delete contact `id`.
+    pub fn delete(&mut self, id: u32) -> Result<Contact, String> {
+        self.contacts
+            .remove(&id)
+            .ok_or_else(|| format!("No contact {}", id))
+    }
+
+    /// Contacts whose name contains `search` (any case), by name.
+    pub fn list(&self, search: Option<&str>) -> Vec<Contact> {
+        let search = search.map(|s| s.to_lowercase());
+        let mut found: Vec<Contact> = self
+            .contacts
+            .values()
+            .filter(|c| {
+                search
+                    .as_deref()
+                    .is_none_or(|s| c.fields.name.to_lowercase().contains(s))
+            })
+            .cloned()
+            .collect();
+        found.sort_by_cached_key(|c| (c.fields.name.to_lowercase(), c.id));
+        found
+    }
+
+    /// Mark contact `id` verified if `fingerprint` is its identity key's.
+    pub fn verify(&mut self, id: u32, fingerprint: &str, now_ms: u64) -> Result<Contact, String> {
+        let contact = self
+            .contacts
+            .get_mut(&id)
+            .ok_or_else(|| format!("No contact {}", id))?;
+        let key = contact
+            .fields
+            .identity_key
+            .as_deref()
+            .and_then(parse_key)
+            .ok_or_else(|| format!("{} has no identity key to verify", contact.fields.name))?;
+        if !fingerprint_matches(&key, fingerprint) {
+            return Err(format!(
+                "Fingerprint does not match {}'s identity key",
+                contact.fields.name
+            ));
+        }
+        if !contact.verified {
+            contact.verified = true;
+            contact.verified_at_ms = Some(now_ms);
+        }
+        Ok(contact.clone())
+    }
+
+    /// Cards of the contacts `ids`, or of every contact.
+    pub fn export(&self, ids: Option<&[u32]>) -> Result<Vec<ContactCard>, String> {
+        match ids {
+            None => Ok(self.contacts.values().map(Contact::to_card).collect()),
+            Some(ids) => ids
+                .iter()
+                .map(|id| {
+                    self.contacts
+                        .get(id)
+                        .map(Contact::to_card)
+                        .ok_or_else(|| format!("No contact {}", id))
+                })
+                .collect(),
+        }
+    }
+
+    /// Merge imported cards.
+    ///
+    /// A card with the identity key of a stored contact adds its endpoints
+    /// to it; the local name is kept. A verified contact's encryption key is
+    /// never replaced by an import, so a card can't redirect messages meant
+    /// for them. Cards whose fingerprint doesn't match their key are
+    /// refused. Returns the counts and, for each card left out or
+    /// partly applied, why.
+    pub fn merge(&mut self, cards: Vec<ContactCard>) -> (MergeCounts, Vec<String>) {
+        let mut counts = MergeCounts::default();
+        let mut warnings = Vec::new();
+        for card in cards {
+            let name = card.name.clone();
+            match self.merge_card(card) {
+                Ok((updated, warning)) => {
+                    if updated {
+                        counts.updated += 1;
+                    } else {
+                        counts.imported += 1;
+                    }
+                    warnings.extend(warning);
+                }
+                Err(e) => warnings.push(format!("{}: {}", name, e)),
+            }
+        }
+        (counts, warnings)
+    }
+
+    /// Merge one card. Returns whether a stored contact was updated, and a
+    /// warning if part of the card was ignored.
+    fn merge_card(&mut self, card: ContactCard) -> Result<(bool, Option<String>), String> {
+        let fields = ContactFields {
+            name: card.name,
+            identity_key: card.identity_key,
+            encryption_key: card.encryption_key,
+            endpoints: card.endpoints,
+            notes: String::new(),
+        }
+        .validate()?;
+        if let (Some(claimed), Some(key)) = (
+            card.fingerprint.as_deref(),
+            fields.identity_key.as_deref().and_then(parse_key),
+        ) {
+            if !fingerprint_matches(&key, claimed) {
+                return Err(String::from("fingerprint does not match identity key"));
+            }
+        }
+
+        let existing = match fields.identity_key.as_deref() {
+            Some(key) => self.find_by_key(key, None),
+            // Keyless contacts can only be matched by name
+            None => self
+                .contacts
+                .values()
+                .find(|c| c.fields.identity_key.is_none() && c.fields.name == fields.name),
+        }
+        .map(|c| c.id);
+        let Some(id) = existing else {
+            return self.add(fields).map(|_| (false, None));
+        };
+
+        let contact = self.contacts.get_mut(&id).unwrap();
+        let mut warning = None;
+        if fields.encryption_key.is_some() && fields.encryption_key != contact.fields.encryption_key
+        {
+            if contact.verified && contact.fields.encryption_key.is_some() {
+                warning = Some(format!(
+                    "{}: kept the encryption key of the verified contact",
+                    contact.fields.name
+                ));
+            } else {
+                contact.fields.encryption_key = fields.encryption_key;
+            }
+        }
+        for endpoint in fields.endpoints {
+            let known = contact
+                .fields
+                .endpoints
+                .iter()
+                .any(|e| e.kind == endpoint.kind && e.address == endpoint.address);
+            if known {
+                continue;
+            }
+            if contact.fields.endpoints.len() >= MAX_ENDPOINTS {
+                warning = Some(format!(
+                    "{}: endpoint limit reached (max {})",
+                    contact.fields.name, MAX_ENDPOINTS
+                ));
+                break;
+            }
+            contact.fields.endpoints.push(endpoint);
+        }
+        Ok((true, warning))
+    }
+
+    fn alloc_id(&mut self) -> u32 {
+        // At most MAX_CONTACTS IDs are taken, so this finds one quickly
+        while self.contacts.contains_key(&self.next_id) || self.next_id == 0 {
+            self.next_id = self.next_id.wrapping_add(1);
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE_KEY: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+    const ALICE_ENC: &str = "2222222222222222222222222222222222222222222222222222222222222222";
+    const OTHER_ENC: &str = "3333333333333333333333333333333333333333333333333333333333333333";
+
+    fn fields(name: &str, identity_key: Option<&str>) -> ContactFields {
+        ContactFields {
+            name: String::from(name),
+            identity_key: identity_key.map(String::from),
+            encryption_key: None,
+            endpoints: Vec::new(),
+            notes: String::new(),
+        }
+    }
+
+    fn relay(address: &str) -> Endpoint {
+        Endpoint {
+            kind: EndpointKind::Relay,
+            address: String::from(address),
+            label: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_add_update_delete() {
+        let mut store = ContactStore::default();
+        let alice = store.add(fields(" Alice ", Some(ALICE_KEY))).unwrap();
+        assert_eq!(alice.fields.name, "Alice");
+        assert!(store.add(fields("Alias", Some(ALICE_KEY))).is_err());
+        assert!(store.add(fields("", None)).is_err());
+        assert!(store.add(fields("Bob", Some("12ab"))).is_err());
+
+        let mut bad = fields("Bob", None);
+        bad.endpoints = alloc::vec![relay("http://relay.example")];
+        assert!(store.add(bad).is_err());
+
+        let mut changed = fields("Alice B.", Some(&ALICE_KEY.to_uppercase()));
+        changed.endpoints = alloc::vec![relay("wss://relay.example")];
+        let updated = store.update(alice.id, changed).unwrap();
+        assert_eq!(updated.fields.identity_key.as_deref(), Some(ALICE_KEY));
+
+        store.delete(alice.id).unwrap();
+        assert!(store.delete(alice.id).is_err());
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_verify_and_key_change() {
+        let mut store = ContactStore::default();
+        let alice = store.add(fields("Alice", Some(ALICE_KEY))).unwrap();
+        let keyless = store.add(fields("Bob", None)).unwrap();
+        let fingerprint = alice.fingerprint().unwrap();
+
+        assert!(store.verify(alice.id, "0000", 5).is_err());
+        assert!(store.verify(keyless.id, &fingerprint, 5).is_err());
+        let verified = store
+            .verify(alice.id, &fingerprint.to_lowercase(), 5)
+            .unwrap();
+        assert!(verified.verified);
+        assert_eq!(verified.verified_at_ms, Some(5));
+
+        // Renaming keeps verification, a new key clears it
+        let renamed = store
+            .update(alice.id, fields("Al", Some(ALICE_KEY)))
+            .unwrap();
+        assert!(renamed.verified);
+        let rekeyed = store
+            .update(alice.id, fields("Al", Some(ALICE_ENC)))
+            .unwrap();
+        assert!(!rekeyed.verified);
+    }
+
+    #[test]
+    fn test_list_search() {
+        let mut store = ContactStore::default();
+        store.add(fields("carol", None)).unwrap();
+        store.add(fields("Alice", None)).unwrap();
+        store.add(fields("Bob", None)).unwrap();
+        let names: Vec<String> = store
+            .list(None)
+            .into_iter()
+            .map(|c| c.fields.name)
+            .collect();
+        assert_eq!(names, ["Alice", "Bob", "carol"]);
+        assert_eq!(store.list(Some("AR")).len(), 1);
+    }
+
+    #[test]
+    fn test_merge_cards() {
+        let mut store = ContactStore::default();
+        let mut alice = fields("Alice", Some(ALICE_KEY));
+        alice.encryption_key = Some(String::from(ALICE_ENC));
+        let alice = store.add(alice).unwrap();
+        let fingerprint = alice.fingerprint().unwrap();
+        store.verify(alice.id, &fingerprint, 1).unwrap();
+
+        let card = ContactCard {
+            name: String::from("Alice (work)"),
+            identity_key: Some(String::from(ALICE_KEY)),
+            encryption_key: Some(String::from(OTHER_ENC)),
+            endpoints: alloc::vec![relay("wss://relay.example")],
+            fingerprint: Some(fingerprint.clone()),
+        };
+        let forged = ContactCard {
+            name: String::from("Mallory"),
+            identity_key: Some(String::from(OTHER_ENC)),
+            fingerprint: Some(fingerprint),
+            ..card.clone()
+        };
+        let (counts, warnings) = store.merge(alloc::vec![card.clone(), forged]);
+        assert_eq!(
+            counts,
+            MergeCounts {
+                imported: 0,
+                updated: 1
+            }
+        );
+        // Kept the verified encryption key, refused the forged card
+        assert_eq!(warnings.len(), 2);
+        let stored = store.get(alice.id).unwrap();
+        assert_eq!(stored.fields.name, "Alice");
+        assert_eq!(stored.fields.encryption_key.as_deref(), Some(ALICE_ENC));
+        assert_eq!(stored.fields.endpoints.len(), 1);
+
+        // Merging again adds nothing
+        store.merge(alloc::vec![card]);
+        assert_eq!(store.get(alice.id).unwrap().fields.endpoints.len(), 1);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_export_and_json_round_trip() {
+        let mut store = ContactStore::default();
+        let mut alice = fields("Alice", Some(ALICE_KEY));
+        alice.notes = String::from("met at the conference");
+        let alice = store.add(alice).unwrap();
+        store.add(fields("Bob", None)).unwrap();
+
+        let cards = store.export(Some(&[alice.id])).unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].fingerprint, alice.fingerprint());
+        assert!(!serde_json::to_string(&cards)
+            .unwrap()
+            .contains("conference"));
+        assert!(store.export(Some(&[99])).is_err());
+        assert_eq!(store.export(None).unwrap().len(), 2);
+
+        let mut saved = ContactStore::from_json(&store.to_json()).unwrap();
+        assert_eq!(saved, store);
+        // IDs carry on after the stored ones
+        assert_eq!(saved.add(fields("Carol", None)).unwrap().id, 3);
+    }
+}
@@ -0,0 +1,513 @@
+//! Topic routing
+//!
+//! Topics are `/`-separated levels such as `system/theme` or
+//! `net/wifi/state`. Subscriptions use MQTT-style patterns: `+` matches
+//! exactly one level and a trailing `#` matches any number of remaining
+//! levels (including none), so `net/#` sees `net` and `net/wifi/state`.
+//!
+//! A publish may be *retained*: the bus keeps the last payload for the topic
+//! and replays it to every later subscriber, which is how late starters learn
+//! the current theme or network state. Retaining a `null` payload clears it.
+//!
+//! Access is controlled per topic by [`TopicAcl`] rules. The most specific
+//! rule matching a topic applies; topics no rule matches are open to
+//! everyone. Publish access is checked when the event is published, subscribe
+//! access for each subscriber when the event is delivered, so a wildcard
+//! subscription only ever sees the topics its owner may read.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Maximum topic or pattern length in bytes.
+pub const MAX_TOPIC_LEN: usize = 128;
+
+/// Maximum serialized payload length in bytes.
+pub const MAX_PAYLOAD_LEN: usize = 4096;
+
+/// Maximum subscriptions across all processes (DoS protection per Rule 11).
+pub const MAX_SUBSCRIPTIONS: usize = 256;
+
+/// Maximum subscriptions held by one process.
+pub const MAX_SUBSCRIPTIONS_PER_PID: usize = 32;
+
+/// Maximum retained events.
+pub const MAX_RETAINED: usize = 256;
+
+/// Maximum access control rules.
+pub const MAX_ACL_RULES: usize = 64;
+
+/// PIDs below this are system processes.
+const SYSTEM_PID_LIMIT: u32 = 10;
+
+/// Who may publish to or subscribe to a topic.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    /// Any process
+    Anyone,
+    /// System processes only
+    System,
+    /// The listed processes (and system processes)
+    Pids(Vec<u32>),
+}
+
+impl Access {
+    /// Whether `pid` is granted this access.
+    pub fn allows(&self, pid: u32) -> bool {
+        match self {
+            Access::Anyone => true,
+            Access::System => pid < SYSTEM_PID_LIMIT,
+            Access::Pids(pids) => pid < SYSTEM_PID_LIMIT || pids.contains(&pid),
+        }
+    }
+}
+
+/// Access rule for the topics matching `pattern`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicAcl {
+    pub pattern: String,
+    pub publish: Access,
+    pub subscribe: Access,
+}
+
+impl TopicAcl {
+    /// Built-in rule: only system processes announce on `system/...`.
+    fn system_default() -> Self {
+        Self {
+            pattern: String::from("system/#"),
+            publish: Access::System,
+            subscribe: Access::Anyone,
+        }
+    }
+}
+
+/// Why a bus operation was refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BusError {
+    /// Empty, too long or malformed topic
+    InvalidTopic,
+    /// Empty, too long or malformed pattern
+    InvalidPattern,
+    /// Payload longer than [`MAX_PAYLOAD_LEN`]
+    PayloadTooLarge,
+    /// The topic's ACL denies the caller
+    Forbidden,
+    /// Subscription limit reached
+    TooManySubscriptions,
+    /// Retained event limit reached
+    TooManyRetained,
+    /// ACL rule limit reached
+    TooManyRules,
+}
+
+impl BusError {
+    /// Message for error responses.
+    pub fn message(self) -> &'static str {
+        match self {
+            BusError::InvalidTopic => "Invalid topic",
+            BusError::InvalidPattern => "Invalid topic pattern",
+            BusError::PayloadTooLarge => "Payload too large",
+            BusError::Forbidden => "Permission denied by topic ACL",
+            BusError::TooManySubscriptions => "Too many subscriptions",
+            BusError::TooManyRetained => "Too many retained events",
+            BusError::TooManyRules => "Too many ACL rules",
+        }
+    }
+}
+
+/// An event as delivered to a subscriber.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EventMessage {
+    /// Subscription that matched
+    pub subscription: u32,
+    pub topic: String,
+    pub payload: Value,
+    /// Publishing process
+    pub publisher: u32,
+    /// Replayed from the retained store rather than freshly published
+    pub retained: bool,
+}
+
+/// An event to be sent to process `pid`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Delivery {
+    pub pid: u32,
+    pub event: EventMessage,
+}
+
+#[derive(Clone, Debug)]
+struct Subscription {
+    id: u32,
+    pid: u32,
+    pattern: String,
+}
+
+#[derive(Clone, Debug)]
+struct Retained {
+    payload: Value,
+    publisher: u32,
+}
+
+/// Subscriptions, retained events and topic ACLs.
+#[derive(Debug)]
+pub struct EventBus {
+    subscriptions: Vec<Subscription>,
+    retained: BTreeMap<String, Retained>,
+    acl: Vec<TopicAcl>,
+    next_id: u32,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            subscriptions: Vec::new(),
+            retained: BTreeMap::new(),
+            acl: alloc::vec![TopicAcl::system_default()],
+            next_id: 0,
+        }
+    }
+}
+
+impl EventBus {
+    /// Publish `payload` on `topic`, returning the deliveries to make.
+    pub fn publish(
+        &mut self,
+        pid: u32,
+        topic: &str,
+        payload: Value,
+        retain: bool,
+    ) -> Result<Vec<Delivery>, BusError> {
+        validate_topic(topic)?;
+        if payload.to_string().len() > MAX_PAYLOAD_LEN {
+            return Err(BusError::PayloadTooLarge);
+        }
+        if !self.rule_for(topic).is_none_or(|r| r.publish.allows(pid)) {
+            return Err(BusError::Forbidden);
+        }
+
+        if retain {
+            if payload.is_null() {
+                self.retained.remove(topic);
+            } else if !self.retained.contains_key(topic) && self.retained.len() >= MAX_RETAINED {
+                return Err(BusError::TooManyRetained);
+            } else {
+                let retained = Retained {
+                    payload: payload.clone(),
+                    publisher: pid,
+                };
+                self.retained.insert(String::from(topic), retained);
+            }
+        }
+
+        let deliveries = self
+            .subscriptions
+            .iter()
+            .filter(|s| topic_matches(&s.pattern, topic) && self.may_subscribe(s.pid, topic))
+            .map(|s| Delivery {
+                pid: s.pid,
+                event: EventMessage {
+                    subscription: s.id,
+                    topic: String::from(topic),
+                    payload: payload.clone(),
+                    publisher: pid,
+                    retained: false,
+                },
+            })
+            .collect();
+        Ok(deliveries)
+    }
+
+    /// Subscribe `pid` to `pattern`, returning the subscription ID and the
+    /// matching retained events to replay.
+    pub fn subscribe(&mut self, pid: u32, pattern: &str) -> Result<(u32, Vec<Delivery>), BusError> {
+        validate_pattern(pattern)?;
+        let held = self.subscriptions.iter().filter(|s| s.pid == pid).count();
+        if self.subscriptions.len() >= MAX_SUBSCRIPTIONS || held >= MAX_SUBSCRIPTIONS_PER_PID {
+            return Err(BusError::TooManySubscriptions);
+        }
+
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        let id = self.next_id;
+        self.subscriptions.push(Subscription {
+            id,
+            pid,
+            pattern: String::from(pattern),
+        });
+
+        let replay = self
+            .retained
+            .iter()
+            .filter(|(topic, _)| topic_matches(pattern, topic) && self.may_subscribe(pid, topic))
+            .map(|(topic, retained)| Delivery {
+                pid,
+                event: EventMessage {
+                    subscription: id,
+                    topic: topic.clone(),
+                    payload: retained.payload.clone(),
+                    publisher: retained.publisher,
+                    retained: true,
+                },
+            })
+            .collect();
+        Ok((id, replay))
+    }
+
+    /// Drop one of `pid`'s subscriptions, or all of them when `id` is `None`.
+    ///
+    /// Returns the number removed; another process's subscription is never
+    /// removed.
+    pub fn unsubscribe(&mut self, pid: u32, id: Option<u32>) -> usize {
+        let before = self.subscriptions.len();
+        self.subscriptions
+            .retain(|s| s.pid != pid || id.is_some_and(|id| s.id != id));
+        before - self.subscriptions.len()
+    }
+
+    /// Add a rule, replacing any rule with the same pattern.
+    pub fn set_acl(&mut self, rule: TopicAcl) -> Result<(), BusError> {
+        validate_pattern(&rule.pattern)?;
+        if let Some(existing) = self.acl.iter_mut().find(|r| r.pattern == rule.pattern) {
+            *existing = rule;
+            return Ok(());
+        }
+        if self.acl.len() >= MAX_ACL_RULES {
+            return Err(BusError::TooManyRules);
+        }
+        self.acl.push(rule);
+        Ok(())
+    }
+
+    /// Current access control rules.
+    pub fn acl(&self) -> &[TopicAcl] {
+        &self.acl
+    }
+
+    /// Number of live subscriptions.
+    pub fn subscription_count(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    /// The most specific rule matching `topic`: an exact rule first, then
+    /// the one with the most literal levels.
+    fn rule_for(&self, topic: &str) -> Option<&TopicAcl> {
+        self.acl
+            .iter()
+            .filter(|r| topic_matches(&r.pattern, topic))
+            .max_by_key(|r| {
+                let exact = !r.pattern.split('/').any(|l| l == "+" || l == "#");
+                let literal = r.pattern.split('/').filter(|l| *l != "+" && *l != "#");
+                (exact, literal.count())
+            })
+    }
+
+    fn may_subscribe(&self, pid: u32, topic: &str) -> bool {
+        self.rule_for(topic).is_none_or(|r| r.subscribe.allows(pid))
+    }
+}
+
+/// Check a concrete topic: non-empty levels of `[A-Za-z0-9._-]`.
+pub fn validate_topic(topic: &str) -> Result<(), BusError> {
+    let valid =
+        !topic.is_empty() && topic.len() <= MAX_TOPIC_LEN && topic.split('/').all(valid_level);
+    if valid {
+        Ok(())
+    } else {
+        Err(BusError::InvalidTopic)
+    }
+}
+
+/// Check a pattern: topic levels, `+` levels, and an optional final `#`.
+pub fn validate_pattern(pattern: &str) -> Result<(), BusError> {
+    let levels: Vec<&str> = pattern.split('/').collect();
+    let valid = !pattern.is_empty()
+        && pattern.len() <= MAX_TOPIC_LEN
+        && levels.iter().enumerate().all(|(i, level)| match *level {
+            "+" => true,
+            "#" => i == levels.len() - 1,
+            level => valid_level(level),
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(BusError::InvalidPattern)
+    }
+}
+
+fn valid_level(level: &str) -> bool {
+    !level.is_empty()
+        && level
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
+}
+
+/// Whether a valid `pattern` matches a valid `topic`.
+pub fn topic_matches(pattern: &str, topic: &str) -> bool {
+    let mut topic_levels = topic.split('/');
+    for level in pattern.split('/') {
+        match level {
+            "#" => return true,
+            "+" => {
+                if topic_levels.next().is_none() {
+                    return false;
+                }
+            }
+            literal => {
+                if topic_levels.next() != Some(literal) {
+                    return false;
+                }
+            }
+        }
+    }
+    topic_levels.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("net/wifi/state", "net/wifi/state"));
+        assert!(topic_matches("net/+/state", "net/wifi/state"));
+        assert!(!topic_matches("net/+", "net/wifi/state"));
+        assert!(topic_matches("net/#", "net/wifi/state"));
+        assert!(topic_matches("net/#", "net"));
+        assert!(topic_matches("#", "anything/at/all"));
+        assert!(!topic_matches("net/wifi", "net"));
+        assert!(!topic_matches("net/wifi/state", "net/wifi"));
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(validate_topic("user/presence").is_ok());
+        for bad in ["", "a//b", "/a", "a/", "a/+", "a/#", "a b"] {
+            assert_eq!(validate_topic(bad), Err(BusError::InvalidTopic), "{bad}");
+        }
+        assert!(validate_pattern("a/+/c/#").is_ok());
+        for bad in ["", "a/#/c", "a/b+", "a//b"] {
+            assert_eq!(
+                validate_pattern(bad),
+                Err(BusError::InvalidPattern),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_publish_reaches_matching_subscribers() {
+        let mut bus = EventBus::default();
+        let (wifi, _) = bus.subscribe(10, "net/wifi/+").unwrap();
+        let (all, _) = bus.subscribe(11, "net/#").unwrap();
+        bus.subscribe(12, "user/#").unwrap();
+
+        let deliveries = bus
+            .publish(20, "net/wifi/state", json!("up"), false)
+            .unwrap();
+        let targets: Vec<(u32, u32)> = deliveries
+            .iter()
+            .map(|d| (d.pid, d.event.subscription))
+            .collect();
+        assert_eq!(targets, [(10, wifi), (11, all)]);
+        assert_eq!(deliveries[0].event.publisher, 20);
+        assert!(!deliveries[0].event.retained);
+    }
+
+    #[test]
+    fn test_retained_replayed_and_cleared() {
+        let mut bus = EventBus::default();
+        bus.publish(1, "system/theme", json!({"mode": "dark"}), true)
+            .unwrap();
+
+        let (id, replay) = bus.subscribe(10, "system/+").unwrap();
+        assert_eq!(replay.len(), 1);
+        assert_eq!(replay[0].event.subscription, id);
+        assert_eq!(replay[0].event.payload, json!({"mode": "dark"}));
+        assert_eq!(replay[0].event.publisher, 1);
+        assert!(replay[0].event.retained);
+
+        bus.publish(1, "system/theme", Value::Null, true).unwrap();
+        let (_, replay) = bus.subscribe(11, "system/theme").unwrap();
+        assert!(replay.is_empty());
+    }
+
+    #[test]
+    fn test_acl_most_specific_rule_wins() {
+        let mut bus = EventBus::default();
+
+        // Default: only system processes publish on system/...
+        assert_eq!(
+            bus.publish(10, "system/theme", json!(1), false),
+            Err(BusError::Forbidden)
+        );
+        bus.publish(3, "system/theme", json!(1), false).unwrap();
+
+        bus.set_acl(TopicAcl {
+            pattern: String::from("system/announcements"),
+            publish: Access::Anyone,
+            subscribe: Access::Anyone,
+        })
+        .unwrap();
+        bus.publish(10, "system/announcements", json!(1), false)
+            .unwrap();
+        assert_eq!(
+            bus.publish(10, "system/other", json!(1), false),
+            Err(BusError::Forbidden)
+        );
+    }
+
+    #[test]
+    fn test_acl_filters_delivery_and_replay() {
+        let mut bus = EventBus::default();
+        bus.set_acl(TopicAcl {
+            pattern: String::from("user/+/presence"),
+            publish: Access::System,
+            subscribe: Access::Pids(alloc::vec![10]),
+        })
+        .unwrap();
+        bus.publish(5, "user/1/presence", json!("online"), true)
+            .unwrap();
+
+        let (_, replay) = bus.subscribe(10, "#").unwrap();
+        assert_eq!(replay.len(), 1);
+        let (_, replay) = bus.subscribe(11, "#").unwrap();
+        assert!(replay.is_empty());
+
+        let deliveries = bus
+            .publish(5, "user/1/presence", json!("away"), false)
+            .unwrap();
+        let pids: Vec<u32> = deliveries.iter().map(|d| d.pid).collect();
+        assert_eq!(pids, [10]);
+    }
+
+    #[test]
+    fn test_unsubscribe_only_own() {
+        let mut bus = EventBus::default();
+        let (a, _) = bus.subscribe(10, "a").unwrap();
+        bus.subscribe(10, "b").unwrap();
+        let (c, _) = bus.subscribe(11, "c").unwrap();
+
+        assert_eq!(bus.unsubscribe(10, Some(c)), 0);
+        assert_eq!(bus.unsubscribe(10, Some(a)), 1);
+        assert_eq!(bus.unsubscribe(10, None), 1);
+        assert_eq!(bus.subscription_count(), 1);
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut bus = EventBus::default();
+        for _ in 0..MAX_SUBSCRIPTIONS_PER_PID {
+            bus.subscribe(10, "a").unwrap();
+        }
+        assert_eq!(bus.subscribe(10, "a"), Err(BusError::TooManySubscriptions));
+
+        let big = Value::String("x".repeat(MAX_PAYLOAD_LEN));
+        assert_eq!(
+            bus.publish(10, "a", big, false),
+            Err(BusError::PayloadTooLarge)
+        );
+    }
+}
@@ -0,0 +1,395 @@
+//! Event Bus Service
+//!
+//! The EventBusService lets loosely-coupled features announce state changes
+//! (theme, network state, user presence) without every interested service
+//! being wired to the publisher. It:
+//! - Routes published events to every subscription whose pattern matches
+//! - Keeps retained events and replays them to new subscribers
+//! - Enforces per-topic publish/subscribe rules
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - PUBLISH: Event handed to every permitted matching subscriber (and stored
+//!   if retained)
+//! - SUBSCRIBE: Subscription recorded AND matching retained events replayed
+//! - SET_ACL: Rule stored and applied to every later publish and delivery
+//!
+//! **Acceptable partial failure:**
+//! - Subscriber exited → its deliveries are dropped by Init; the
+//!   subscription lingers until unsubscribed, bounded by the per-process limit
+//!
+//! **Forbidden:**
+//! - Events reaching a process the topic's ACL does not allow to subscribe
+//! - Unprivileged processes changing ACLs
+//! - Unbounded subscription or retained growth (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_EVENT_PUBLISH (0x8600)`: Publish, optionally retaining the payload
+//! - `MSG_EVENT_SUBSCRIBE (0x8602)`: Subscribe to a topic pattern
+//! - `MSG_EVENT_UNSUBSCRIBE (0x8604)`: Drop one or all of the caller's subscriptions
+//! - `MSG_EVENT (0x8606)`: Event delivered to a subscriber
+//! - `MSG_EVENT_SET_ACL (0x8608)`: Set a topic access rule
+//!
+//! The service holds no capabilities to subscribers, so events are handed to
+//! the supervisor as `EVENT:DELIVER:{pid}:{tag}:{hex}` on the debug channel
+//! and routed through Init like VFS responses.
+
+extern crate alloc;
+
+pub mod bus;
+
+use crate::manifests::EVENTS_MANIFEST;
+use crate::response::JsonResponder;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use serde_json::Value;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+
+pub use bus::{
+    Access, BusError, Delivery, EventBus, EventMessage, TopicAcl, MAX_PAYLOAD_LEN, MAX_TOPIC_LEN,
+};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for event bus service - re-exported from zos-ipc.
+pub mod events_msg {
+    pub use zos_ipc::events::*;
+}
+
+// =============================================================================
+// Permission Constants
+// =============================================================================
+
+/// PIDs allowed to change topic ACLs.
+/// - PID 0: Supervisor
+/// - PID 1: Init
+/// - PID 3: Desktop/Settings UI
+const TRUSTED_PIDS_FOR_ACL: &[u32] = &[0, 1, 3];
+
+// =============================================================================
+// Request Types
+// =============================================================================
+
+/// Payload of MSG_EVENT_PUBLISH.
+#[derive(Clone, Debug, Deserialize)]
+struct PublishRequest {
+    topic: String,
+    #[serde(default)]
+    payload: Value,
+    #[serde(default)]
+    retain: bool,
+}
+
+/// Payload of MSG_EVENT_SUBSCRIBE.
+#[derive(Clone, Debug, Deserialize)]
+struct SubscribeRequest {
+    pattern: String,
+}
+
+/// Payload of MSG_EVENT_UNSUBSCRIBE.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct UnsubscribeRequest {
+    #[serde(default)]
+    id: Option<u32>,
+}
+
+// =============================================================================
+// EventBusService Application
+// =============================================================================
+
+/// EventBusService - topic-based publish/subscribe
+#[derive(Default)]
+pub struct EventBusService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// Subscriptions, retained events and ACLs
+    bus: EventBus,
+}
+
+impl EventBusService {
+    /// Check if caller may change ACLs (Rule 4: fail-closed).
+    fn check_acl_permission(&self, from_pid: u32) -> bool {
+        let allowed = TRUSTED_PIDS_FOR_ACL.contains(&from_pid);
+        if !allowed {
+            syscall::debug(&format!(
+                "EventBusService: SECURITY - SET_ACL denied for PID {}",
+                from_pid
+            ));
+        }
+        allowed
+    }
+
+    /// Hand events to the supervisor for delivery through Init.
+    fn deliver(&self, deliveries: &[Delivery]) {
+        for delivery in deliveries {
+            let json = serde_json::to_vec(&delivery.event).unwrap_or_default();
+            let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+            syscall::debug(&format!(
+                "{}{}:{:08x}:{}",
+                zos_ipc::debug::EVENT_DELIVER,
+                delivery.pid,
+                events_msg::MSG_EVENT,
+                hex
+            ));
+        }
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_EVENT_PUBLISH
+    fn handle_publish(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = events_msg::MSG_EVENT_PUBLISH_RESPONSE;
+        let request: PublishRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid publish request: expected {\"topic\": string, \"payload\": any}",
+                );
+            }
+        };
+
+        let deliveries = match self.bus.publish(
+            msg.from_pid,
+            &request.topic,
+            request.payload,
+            request.retain,
+        ) {
+            Ok(d) => d,
+            Err(e) => {
+                if e == BusError::Forbidden {
+                    syscall::debug(&format!(
+                        "EventBusService: SECURITY - PID {} may not publish to {}",
+                        msg.from_pid, request.topic
+                    ));
+                }
+                return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, e.message());
+            }
+        };
+
+        self.deliver(&deliveries);
+        let json = format!(r#"{{"delivered":{}}}"#, deliveries.len());
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, json.as_bytes())
+    }
+
+    /// Handle MSG_EVENT_SUBSCRIBE
+    fn handle_subscribe(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = events_msg::MSG_EVENT_SUBSCRIBE_RESPONSE;
+        let request: SubscribeRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid subscribe request: expected {\"pattern\": string}",
+                );
+            }
+        };
+
+        let (id, replay) = match self.bus.subscribe(msg.from_pid, &request.pattern) {
+            Ok(r) => r,
+            Err(e) => {
+                return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, e.message());
+            }
+        };
+
+        // Respond first so the subscriber knows the ID before replays arrive
+        let json = format!(r#"{{"id":{}}}"#, id);
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, json.as_bytes())?;
+        self.deliver(&replay);
+        Ok(())
+    }
+
+    /// Handle MSG_EVENT_UNSUBSCRIBE
+    fn handle_unsubscribe(&mut self, msg: &Message) -> Result<(), AppError> {
+        // An empty payload drops everything the caller holds
+        let request: UnsubscribeRequest = if msg.data.is_empty() {
+            UnsubscribeRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        events_msg::MSG_EVENT_UNSUBSCRIBE_RESPONSE,
+                        "Invalid unsubscribe request: expected {\"id\": u32?}",
+                    );
+                }
+            }
+        };
+
+        let removed = self.bus.unsubscribe(msg.from_pid, request.id);
+        let json = format!(r#"{{"removed":{}}}"#, removed);
+        self.send_response(
+            msg.from_pid,
+            &msg.cap_slots,
+            events_msg::MSG_EVENT_UNSUBSCRIBE_RESPONSE,
+            json.as_bytes(),
+        )
+    }
+
+    /// Handle MSG_EVENT_SET_ACL
+    fn handle_set_acl(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = events_msg::MSG_EVENT_SET_ACL_RESPONSE;
+
+        if !self.check_acl_permission(msg.from_pid) {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: SET_ACL requires system privilege",
+            );
+        }
+        let rule: TopicAcl = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid ACL: expected {\"pattern\", \"publish\", \"subscribe\"}",
+                );
+            }
+        };
+
+        syscall::debug(&format!(
+            "EventBusService: PID {} sets ACL for {}: publish {:?}, subscribe {:?}",
+            msg.from_pid, rule.pattern, rule.publish, rule.subscribe
+        ));
+        if let Err(e) = self.bus.set_acl(rule) {
+            return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, e.message());
+        }
+
+        let json =
+            serde_json::to_vec(&serde_json::json!({ "rules": self.bus.acl() })).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+}
+
+impl JsonResponder for EventBusService {
+    const SERVICE_NAME: &'static str = "EventBusService";
+}
+
+impl ZeroApp for EventBusService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &EVENTS_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("EventBusService starting (PID {})", ctx.pid));
+
+        // Register with init as "events" service
+        let service_name = "events";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("EventBusService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, _ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        match msg.tag {
+            events_msg::MSG_EVENT_PUBLISH => self.handle_publish(&msg),
+            events_msg::MSG_EVENT_SUBSCRIBE => self.handle_subscribe(&msg),
+            events_msg::MSG_EVENT_UNSUBSCRIBE => self.handle_unsubscribe(&msg),
+            events_msg::MSG_EVENT_SET_ACL => self.handle_set_acl(&msg),
+            _ => {
+                syscall::debug(&format!(
+                    "EventBusService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("EventBusService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+
+    fn subscribe(service: &mut EventBusService, pid: u32, pattern: &str) {
+        let json = format!(r#"{{"pattern":"{}"}}"#, pattern);
+        let msg = mock_message(events_msg::MSG_EVENT_SUBSCRIBE, pid, json.into_bytes());
+        service.handle_subscribe(&msg).unwrap();
+    }
+
+    #[test]
+    fn test_publish_request_defaults() {
+        let request: PublishRequest = serde_json::from_str(r#"{"topic":"a"}"#).unwrap();
+        assert!(request.payload.is_null());
+        assert!(!request.retain);
+    }
+
+    #[test]
+    fn test_subscribe_and_unsubscribe() {
+        let mut service = EventBusService::default();
+        subscribe(&mut service, 10, "net/#");
+        subscribe(&mut service, 10, "user/+/presence");
+        subscribe(&mut service, 10, "bad//pattern");
+        assert_eq!(service.bus.subscription_count(), 2);
+
+        let msg = mock_message(events_msg::MSG_EVENT_UNSUBSCRIBE, 10, Vec::new());
+        service.handle_unsubscribe(&msg).unwrap();
+        assert_eq!(service.bus.subscription_count(), 0);
+    }
+
+    #[test]
+    fn test_set_acl_requires_trusted_pid() {
+        let service = EventBusService::default();
+        for &pid in TRUSTED_PIDS_FOR_ACL {
+            assert!(service.check_acl_permission(pid));
+        }
+        assert!(!service.check_acl_permission(100));
+
+        let rule = br#"{"pattern":"net/#","publish":{"pids":[20]},"subscribe":"anyone"}"#;
+        let mut service = EventBusService::default();
+        let msg = mock_message(events_msg::MSG_EVENT_SET_ACL, 100, rule.to_vec());
+        service.handle_set_acl(&msg).unwrap();
+        assert_eq!(service.bus.acl().len(), 1);
+
+        let msg = mock_message(events_msg::MSG_EVENT_SET_ACL, 3, rule.to_vec());
+        service.handle_set_acl(&msg).unwrap();
+        assert_eq!(service.bus.acl().len(), 2);
+        assert_eq!(service.bus.acl()[1].publish, Access::Pids(alloc::vec![20]));
+    }
+}
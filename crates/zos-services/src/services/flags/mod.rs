@@ -0,0 +1,619 @@
+//! Feature Flag Service
+//!
+//! The FeatureFlagService owns the persistent feature flag store. It:
+//! - Loads `/system/config/flags.json` via VFS at startup
+//! - Resolves system-wide values and per-user overrides on request
+//! - Validates and persists flag changes before acknowledging them
+//! - Publishes the system-wide snapshot so the supervisor can gate
+//!   subsystems without rebuilding
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - SET: Change validated AND written to VFS AND in-memory store updated
+//!
+//! **Acceptable partial failure:**
+//! - Store file missing or corrupt at startup → built-in defaults
+//!
+//! **Forbidden:**
+//! - Acknowledging a SET before the store is persisted
+//! - Allowing unauthorized processes to change flags
+//! - Unbounded pending operations (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_FLAGS_GET (0x8300)`: Resolve flags for a user (or system-wide)
+//! - `MSG_FLAGS_SET (0x8302)`: Set or clear a system value or user override
+//! - `MSG_FLAGS_LIST (0x8304)`: Raw stored values and built-in definitions
+//!
+//! After loading and after every change the service emits
+//! `FLAGS:SNAPSHOT:{hex_json}` on the debug channel with the resolved
+//! system-wide [`zos_flags::FeatureFlags`].
+
+extern crate alloc;
+
+use crate::manifests::FLAGS_MANIFEST;
+use crate::response::JsonResponder;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+use zos_flags::{FlagStore, FLAGS_PATH, KNOWN_FLAGS};
+use zos_vfs::async_client;
+use zos_vfs::ipc::vfs_msg;
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for feature flag service - re-exported from zos-ipc.
+pub mod flags_msg {
+    pub use zos_ipc::flags::*;
+}
+
+// =============================================================================
+// Permission & Limit Constants
+// =============================================================================
+
+/// Maximum number of pending VFS operations (DoS protection per Rule 11)
+const MAX_PENDING_OPS: usize = 16;
+
+/// Maximum messages buffered while the store is loading (Rule 11)
+const MAX_DEFERRED_MESSAGES: usize = 8;
+
+/// PIDs allowed to change flags.
+/// - PID 0: Supervisor
+/// - PID 1: Init
+/// - PID 3: Desktop/Settings UI
+const TRUSTED_PIDS_FOR_SET: &[u32] = &[0, 1, 3];
+
+// =============================================================================
+// Request / Response Types
+// =============================================================================
+
+/// Payload of MSG_FLAGS_GET.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct GetRequest {
+    #[serde(default)]
+    user_id: Option<String>,
+}
+
+/// Payload of MSG_FLAGS_SET.
+#[derive(Clone, Debug, Deserialize)]
+struct SetRequest {
+    name: String,
+    /// `null` clears the value
+    enabled: Option<bool>,
+    #[serde(default)]
+    user_id: Option<String>,
+}
+
+/// Built-in flag definition as reported by MSG_FLAGS_LIST.
+#[derive(Clone, Debug, Serialize)]
+struct KnownFlag {
+    name: &'static str,
+    default: bool,
+    description: &'static str,
+}
+
+/// Payload of MSG_FLAGS_LIST_RESPONSE.
+#[derive(Clone, Debug, Serialize)]
+struct ListResponse<'a> {
+    store: &'a FlagStore,
+    known: Vec<KnownFlag>,
+}
+
+/// Parse a 32-char hex user ID.
+fn parse_user_id(hex: &str) -> Option<u128> {
+    if hex.len() != 32 {
+        return None;
+    }
+    u128::from_str_radix(hex, 16).ok()
+}
+
+// =============================================================================
+// Pending Operations
+// =============================================================================
+
+/// Pending VFS operations, matched oldest-first by [`OpType`].
+#[derive(Clone, Debug)]
+enum PendingOp {
+    /// Initial load of the flag store
+    LoadStore,
+    /// Persist a flag change
+    CommitSet {
+        client_pid: u32,
+        cap_slots: Vec<u32>,
+        user_id: Option<u128>,
+        store: FlagStore,
+    },
+}
+
+/// Operation type for matching VFS responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OpType {
+    Read,
+    Write,
+}
+
+// =============================================================================
+// FeatureFlagService Application
+// =============================================================================
+
+/// FeatureFlagService - persistent feature flags with per-user overrides
+#[derive(Default)]
+pub struct FeatureFlagService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// Stored flag values (valid once `loaded`)
+    store: FlagStore,
+    /// Whether the store has been loaded from VFS
+    loaded: bool,
+    /// Pending VFS operations: request_id -> (operation, op_type)
+    pending_ops: BTreeMap<u32, (PendingOp, OpType)>,
+    /// Requests received before the store finished loading
+    deferred: Vec<Message>,
+    /// Next request ID for VFS correlation
+    next_request_id: u32,
+}
+
+impl FeatureFlagService {
+    /// Allocate a new request ID for operation correlation.
+    fn alloc_request_id(&mut self) -> u32 {
+        self.next_request_id = self.next_request_id.wrapping_add(1).max(1);
+        self.next_request_id
+    }
+
+    /// Find and remove the oldest pending VFS operation of the given type.
+    fn take_pending_by_type(&mut self, op_type: OpType) -> Option<PendingOp> {
+        let request_id = self
+            .pending_ops
+            .iter()
+            .find(|(_, (_, t))| *t == op_type)
+            .map(|(id, _)| *id)?;
+        self.pending_ops.remove(&request_id).map(|(op, _)| op)
+    }
+
+    /// Check if caller may change flags (Rule 4: fail-closed).
+    fn check_set_permission(&self, from_pid: u32) -> bool {
+        let allowed = TRUSTED_PIDS_FOR_SET.contains(&from_pid);
+        if !allowed {
+            syscall::debug(&format!(
+                "FeatureFlagService: SECURITY - SET denied for PID {}",
+                from_pid
+            ));
+        }
+        allowed
+    }
+
+    /// Check and enforce pending operation limits (DoS protection per Rule 11).
+    fn check_pending_limit(&self) -> bool {
+        if self.pending_ops.len() >= MAX_PENDING_OPS {
+            syscall::debug(&format!(
+                "FeatureFlagService: Pending operation limit reached ({}/{})",
+                self.pending_ops.len(),
+                MAX_PENDING_OPS
+            ));
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Apply a SET request to a copy of the store.
+    fn apply_set(&self, request: &SetRequest) -> Result<(FlagStore, Option<u128>), String> {
+        let user_id = match request.user_id.as_deref() {
+            Some(hex) => {
+                Some(parse_user_id(hex).ok_or_else(|| format!("Invalid user_id: {:?}", hex))?)
+            }
+            None => None,
+        };
+        let mut next = self.store.clone();
+        let result = match user_id {
+            Some(id) => next.set_user(id, &request.name, request.enabled),
+            None => next.set_system(&request.name, request.enabled),
+        };
+        result.map_err(|e| e.message())?;
+        Ok((next, user_id))
+    }
+
+    /// Publish the system-wide snapshot for the supervisor.
+    fn broadcast_snapshot(&self) {
+        let json = self.store.resolve(None).to_json();
+        let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+        syscall::debug(&format!("{}{}", zos_ipc::debug::FLAGS_SNAPSHOT, hex));
+    }
+}
+
+impl FeatureFlagService {
+    // =========================================================================
+    // VFS helpers (async, non-blocking)
+    // =========================================================================
+
+    fn start_vfs_read(&mut self, path: &str, op: PendingOp) -> Result<(), AppError> {
+        let request_id = self.alloc_request_id();
+        async_client::send_read_request(path)?;
+        self.pending_ops.insert(request_id, (op, OpType::Read));
+        Ok(())
+    }
+
+    fn start_vfs_write(&mut self, path: &str, value: &[u8], op: PendingOp) -> Result<(), AppError> {
+        let request_id = self.alloc_request_id();
+        async_client::send_write_request(path, value)?;
+        self.pending_ops.insert(request_id, (op, OpType::Write));
+        Ok(())
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_FLAGS_GET
+    fn handle_get(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = flags_msg::MSG_FLAGS_GET_RESPONSE;
+        let request: GetRequest = if msg.data.is_empty() {
+            GetRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid get request: expected {\"user_id\": string?}",
+                    );
+                }
+            }
+        };
+
+        let user_id = match request.user_id.as_deref() {
+            Some(hex) => match parse_user_id(hex) {
+                Some(id) => Some(id),
+                None => {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        &format!("Invalid user_id: {:?}", hex),
+                    );
+                }
+            },
+            None => None,
+        };
+
+        let json = self.store.resolve(user_id).to_json();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_FLAGS_SET
+    fn handle_set(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = flags_msg::MSG_FLAGS_SET_RESPONSE;
+
+        if !self.check_set_permission(msg.from_pid) {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: FLAGS_SET requires system privilege",
+            );
+        }
+        if !self.check_pending_limit() {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Service busy: pending operation limit reached",
+            );
+        }
+
+        let request: SetRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid set request: expected {\"name\": string, \"enabled\": bool|null}",
+                );
+            }
+        };
+
+        let (store, user_id) = match self.apply_set(&request) {
+            Ok(r) => r,
+            Err(e) => return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        };
+
+        syscall::debug(&format!(
+            "FeatureFlagService: PID {} sets {}={:?}{}",
+            msg.from_pid,
+            request.name,
+            request.enabled,
+            match user_id {
+                Some(id) => format!(" for user {:032x}", id),
+                None => String::new(),
+            }
+        ));
+
+        let value = store.to_json();
+        let op = PendingOp::CommitSet {
+            client_pid: msg.from_pid,
+            cap_slots: msg.cap_slots.clone(),
+            user_id,
+            store,
+        };
+        if let Err(e) = self.start_vfs_write(FLAGS_PATH, &value, op) {
+            return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e.to_string());
+        }
+        Ok(())
+    }
+
+    /// Handle MSG_FLAGS_LIST
+    fn handle_list(&mut self, msg: &Message) -> Result<(), AppError> {
+        let response = ListResponse {
+            store: &self.store,
+            known: KNOWN_FLAGS
+                .iter()
+                .map(|f| KnownFlag {
+                    name: f.name,
+                    default: f.default,
+                    description: f.description,
+                })
+                .collect(),
+        };
+        let json = serde_json::to_vec(&response).unwrap_or_default();
+        self.send_response(
+            msg.from_pid,
+            &msg.cap_slots,
+            flags_msg::MSG_FLAGS_LIST_RESPONSE,
+            &json,
+        )
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Handle VFS read response (MSG_VFS_READ_RESPONSE)
+    fn handle_vfs_read_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Some(op) = self.take_pending_by_type(OpType::Read) else {
+            syscall::debug("FeatureFlagService: VFS read response but no pending read");
+            return Ok(());
+        };
+        if !matches!(op, PendingOp::LoadStore) {
+            return Ok(());
+        }
+
+        self.store = match async_client::parse_read_response(&msg.data) {
+            Ok(data) => FlagStore::from_json(&data).unwrap_or_else(|| {
+                syscall::debug(&format!(
+                    "FeatureFlagService: {} is corrupt, using defaults",
+                    FLAGS_PATH
+                ));
+                FlagStore::default()
+            }),
+            Err(_) => FlagStore::default(),
+        };
+        self.loaded = true;
+        syscall::debug(&format!(
+            "FeatureFlagService: loaded {} system flags, {} users with overrides",
+            self.store.system.len(),
+            self.store.users.len()
+        ));
+        self.broadcast_snapshot();
+        self.replay_deferred()
+    }
+
+    /// Handle VFS write response (MSG_VFS_WRITE_RESPONSE)
+    fn handle_vfs_write_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Some(op) = self.take_pending_by_type(OpType::Write) else {
+            syscall::debug("FeatureFlagService: VFS write response but no pending write");
+            return Ok(());
+        };
+        let PendingOp::CommitSet {
+            client_pid,
+            cap_slots,
+            user_id,
+            store,
+        } = op
+        else {
+            return Ok(());
+        };
+        let tag = flags_msg::MSG_FLAGS_SET_RESPONSE;
+
+        match async_client::parse_write_response(&msg.data) {
+            Ok(()) => {
+                self.store = store;
+                self.broadcast_snapshot();
+                let json = self.store.resolve(user_id).to_json();
+                self.send_response(client_pid, &cap_slots, tag, &json)
+            }
+            Err(e) => self.send_error_response(
+                client_pid,
+                &cap_slots,
+                tag,
+                &format!("VFS write failed for {}: {}", FLAGS_PATH, e),
+            ),
+        }
+    }
+
+    /// Buffer a request until the store is loaded.
+    fn defer(&mut self, msg: Message) -> Result<(), AppError> {
+        if self.deferred.len() >= MAX_DEFERRED_MESSAGES {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                msg.tag + 1,
+                "Service busy: flag store still loading",
+            );
+        }
+        self.deferred.push(msg);
+        Ok(())
+    }
+
+    fn replay_deferred(&mut self) -> Result<(), AppError> {
+        for msg in core::mem::take(&mut self.deferred) {
+            self.dispatch_request(msg)?;
+        }
+        Ok(())
+    }
+
+    fn dispatch_request(&mut self, msg: Message) -> Result<(), AppError> {
+        if !self.loaded {
+            return self.defer(msg);
+        }
+        match msg.tag {
+            flags_msg::MSG_FLAGS_GET => self.handle_get(&msg),
+            flags_msg::MSG_FLAGS_SET => self.handle_set(&msg),
+            flags_msg::MSG_FLAGS_LIST => self.handle_list(&msg),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl JsonResponder for FeatureFlagService {
+    const SERVICE_NAME: &'static str = "FeatureFlagService";
+}
+
+impl ZeroApp for FeatureFlagService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &FLAGS_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("FeatureFlagService starting (PID {})", ctx.pid));
+
+        // Register with init as "flags" service
+        let service_name = "flags";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("FeatureFlagService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+
+        self.start_vfs_read(FLAGS_PATH, PendingOp::LoadStore)
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, _ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        match msg.tag {
+            // VFS responses (Invariant 31 compliant - storage via VFS IPC)
+            vfs_msg::MSG_VFS_READ_RESPONSE => self.handle_vfs_read_response(&msg),
+            vfs_msg::MSG_VFS_WRITE_RESPONSE => self.handle_vfs_write_response(&msg),
+
+            // Feature flag protocol
+            flags_msg::MSG_FLAGS_GET | flags_msg::MSG_FLAGS_SET | flags_msg::MSG_FLAGS_LIST => {
+                self.dispatch_request(msg)
+            }
+
+            _ => {
+                syscall::debug(&format!(
+                    "FeatureFlagService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("FeatureFlagService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+    use zos_flags::known;
+
+    const USER: &str = "000000000000000000000000000a11ce";
+
+    fn set_request(json: &str) -> SetRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_set_permission_trusted_pids() {
+        let service = FeatureFlagService::default();
+        for &pid in TRUSTED_PIDS_FOR_SET {
+            assert!(service.check_set_permission(pid));
+        }
+        assert!(!service.check_set_permission(100));
+    }
+
+    #[test]
+    fn test_apply_set_system_and_user() {
+        let service = FeatureFlagService::default();
+        let (store, user) = service
+            .apply_set(&set_request(r#"{"name":"vfs.journal","enabled":true}"#))
+            .unwrap();
+        assert_eq!(user, None);
+        assert!(store.resolve(None).is_enabled(known::VFS_JOURNAL));
+        // The live store is untouched until the write is acknowledged
+        assert!(service.store.system.is_empty());
+
+        let json = format!(
+            r#"{{"name":"vfs.journal","enabled":false,"user_id":"{}"}}"#,
+            USER
+        );
+        let (store, user) = service.apply_set(&set_request(&json)).unwrap();
+        assert_eq!(user, Some(0xA11CE));
+        assert!(!store.resolve(user).is_enabled(known::VFS_JOURNAL));
+    }
+
+    #[test]
+    fn test_apply_set_rejects_bad_input() {
+        let service = FeatureFlagService::default();
+        assert!(service
+            .apply_set(&set_request(r#"{"name":"Bad Name","enabled":true}"#))
+            .is_err());
+        assert!(service
+            .apply_set(&set_request(
+                r#"{"name":"vfs.journal","enabled":true,"user_id":"xyz"}"#
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn test_set_request_null_clears() {
+        let request = set_request(r#"{"name":"vfs.journal","enabled":null}"#);
+        assert_eq!(request.enabled, None);
+    }
+
+    #[test]
+    fn test_requests_deferred_until_loaded() {
+        let mut service = FeatureFlagService::default();
+        let msg = mock_message(flags_msg::MSG_FLAGS_GET, 3, Vec::new());
+        service.dispatch_request(msg).unwrap();
+        assert_eq!(service.deferred.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_user_id() {
+        assert_eq!(parse_user_id(USER), Some(0xA11CE));
+        assert_eq!(parse_user_id("a11ce"), None);
+    }
+}
@@ -6,7 +6,7 @@
 //!
 //! ## Success Conditions
 //! - Authorization check succeeds only when:
-//!   1. Caller PID is in the trusted process list, OR
+//!   1. Caller is in the trusted process list, OR
 //!   2. (Future) Caller presents a valid session token for the target user
 //!
 //! ## Forbidden States
@@ -17,8 +17,14 @@
 //! # Security Model
 //!
 //! ## Trusted Processes (Phase 1 - Current)
-//! - **System processes** (PID 1-9): Kernel-level services with full access
-//! - **Desktop process** (PID 10): Trusted user-facing application
+//! - **System processes**: the supervisor (PID 0, which relays the web
+//!   desktop's requests), Init (PID 1) and the PermissionService
+//! - **Desktop process**: Trusted user-facing application
+//!
+//! Callers other than the supervisor and Init are recognized by the name
+//! they run under, never by PID (see `crate::trust`): boot services spawn
+//! in dependency order, so the PID a service gets says nothing about it.
+//! Other boot services act on no user's identity and are not trusted.
 //!
 //! ## Future Enhancement (Phase 2)
 //! - Session token verification via Permission Service
@@ -26,36 +32,32 @@
 //!
 //! # Authorization Flow
 //!
-//! 1. Check if caller PID is in TRUSTED_SYSTEM_PIDS → ALLOW
-//! 2. Check if caller PID is DESKTOP_PROCESS_PID → ALLOW (with session validation TODO)
+//! 1. Check if caller is a trusted system process → ALLOW
+//! 2. Check if caller is the desktop process → ALLOW (with session validation TODO)
 //! 3. (Future) Verify session token with Permission Service
 //! 4. On ANY uncertainty → DENY (fail-closed)
 
 use alloc::format;
+use alloc::string::String;
 use zos_apps::syscall;
+use zos_process::{pid, services};
+
+use crate::trust;
 
 // =============================================================================
 // Trusted Process Configuration
 // =============================================================================
 
-/// System processes with implicit trust (kernel-level services).
-/// These PIDs are assigned during boot and never change.
-///
-/// - PID 0: Init process (not used for IPC)
-/// - PID 1: Init service handler
-/// - PID 2: Permission service
-/// - PID 3: Identity service (this service)
-/// - PID 4: VFS service
-/// - PID 5: Time service
-/// - PIDs 6-9: Reserved for future system services
-const TRUSTED_SYSTEM_PID_MAX: u32 = 9;
-
-/// The desktop process PID - the trusted user-facing application.
+/// Services trusted to act on any user's identity, by the name they run
+/// under. The supervisor and Init are trusted by their fixed PIDs.
+const TRUSTED_SERVICES: &[&str] = &[services::PERMISSION];
+
+/// The desktop process - the trusted user-facing application.
 /// Desktop mediates all user interactions and validates user sessions.
 ///
 /// NOTE: In Phase 2, this will require session token verification
-/// instead of implicit PID trust.
-const DESKTOP_PROCESS_PID: u32 = 10;
+/// instead of implicit trust.
+const DESKTOP_PROCESS_NAME: &str = services::DESKTOP;
 
 // =============================================================================
 // Authorization Types
@@ -73,9 +75,9 @@ pub enum AuthResult {
 /// Reason for allowing or denying authorization (for logging/debugging).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AuthReason {
-    /// Allowed: Caller is a system process (PID < 10)
+    /// Allowed: Caller is a trusted system process
     SystemProcess,
-    /// Allowed: Caller is the desktop process (PID 10)
+    /// Allowed: Caller is the desktop process
     DesktopProcess,
     /// Allowed: Session token verified (future)
     #[allow(dead_code)]
@@ -97,8 +99,8 @@ pub enum AuthReason {
 ///
 /// This function implements fail-closed authorization:
 /// - If authorization cannot be determined → DENY
-/// - System processes (PID <= TRUSTED_SYSTEM_PID_MAX) have implicit trust
-/// - Desktop process (PID == DESKTOP_PROCESS_PID) is trusted
+/// - The supervisor, Init and `TRUSTED_SERVICES` have implicit trust
+/// - The desktop process is trusted
 /// - All other processes are denied by default
 ///
 /// # Arguments
@@ -111,7 +113,12 @@ pub enum AuthReason {
 /// * `AuthResult::Allowed` - Proceed with the operation
 /// * `AuthResult::Denied` - Reject the request with Unauthorized error
 pub fn check_user_authorization(from_pid: u32, target_user_id: u128) -> AuthResult {
-    let (result, reason) = check_authorization_with_reason(from_pid, target_user_id);
+    let name = match from_pid {
+        pid::SUPERVISOR | pid::INIT => None,
+        _ => trust::process_name(from_pid),
+    };
+    let (result, reason) =
+        check_authorization_with_reason(from_pid, name.as_deref(), target_user_id);
 
     // Log the decision with reason
     match result {
@@ -123,8 +130,11 @@ pub fn check_user_authorization(from_pid: u32, target_user_id: u128) -> AuthResu
         }
         AuthResult::Denied => {
             syscall::debug(&format!(
-                "IdentityService: Auth DENY - PID {} for user {:032x} (reason: {:?})",
-                from_pid, target_user_id, reason
+                "IdentityService: Auth DENY - PID {} ({}) for user {:032x} (reason: {:?})",
+                from_pid,
+                name.unwrap_or_else(|| String::from("unknown")),
+                target_user_id,
+                reason
             ));
         }
     }
@@ -133,20 +143,26 @@ pub fn check_user_authorization(from_pid: u32, target_user_id: u128) -> AuthResu
 }
 
 /// Check authorization with detailed reason (for internal use and testing).
-fn check_authorization_with_reason(from_pid: u32, _target_user_id: u128) -> (AuthResult, AuthReason) {
+///
+/// `name` is the name the caller runs under, if it is running.
+fn check_authorization_with_reason(
+    from_pid: u32,
+    name: Option<&str>,
+    _target_user_id: u128,
+) -> (AuthResult, AuthReason) {
     // Check 1: System processes have full access
-    // These are trusted kernel-level services with assigned PIDs
-    if is_system_process(from_pid) {
+    if is_system_process(from_pid, name) {
         return (AuthResult::Allowed, AuthReason::SystemProcess);
     }
 
     // Check 2: Desktop process is trusted to act on behalf of users
-    // NOTE: In Phase 2, this should verify session token instead of trusting PID
+    // NOTE: In Phase 2, this should verify session token instead of trusting
+    // the process
     // TODO: Implement session token verification:
     //   1. Desktop should include session_token in request
     //   2. Verify token with Permission Service
     //   3. Extract user_id from token and compare with target_user_id
-    if is_desktop_process(from_pid) {
+    if is_desktop_process(name) {
         return (AuthResult::Allowed, AuthReason::DesktopProcess);
     }
 
@@ -170,16 +186,19 @@ pub fn log_denial(operation: &str, from_pid: u32, target_user_id: u128) {
     ));
 }
 
-/// Check if a PID is a system process (for external use).
+/// Check if a caller is a trusted system process, given the name it runs
+/// under.
 #[inline]
-pub fn is_system_process(pid: u32) -> bool {
-    pid <= TRUSTED_SYSTEM_PID_MAX
+pub fn is_system_process(pid: u32, name: Option<&str>) -> bool {
+    pid == pid::SUPERVISOR
+        || pid == pid::INIT
+        || name.is_some_and(|name| TRUSTED_SERVICES.contains(&name))
 }
 
-/// Check if a PID is the desktop process (for external use).
+/// Check if a caller is the desktop process, given the name it runs under.
 #[inline]
-pub fn is_desktop_process(pid: u32) -> bool {
-    pid == DESKTOP_PROCESS_PID
+pub fn is_desktop_process(name: Option<&str>) -> bool {
+    name == Some(DESKTOP_PROCESS_NAME)
 }
 
 #[cfg(test)]
@@ -192,16 +211,23 @@ mod tests {
 
     #[test]
     fn test_system_process_allowed() {
-        // System processes (PID <= 9) should always be allowed
+        // The supervisor and Init are trusted by PID
         assert_eq!(check_user_authorization(0, 12345), AuthResult::Allowed);
         assert_eq!(check_user_authorization(1, 12345), AuthResult::Allowed);
-        assert_eq!(check_user_authorization(3, 12345), AuthResult::Allowed);
-        assert_eq!(check_user_authorization(9, 12345), AuthResult::Allowed);
+        assert_eq!(
+            check_authorization_with_reason(2, Some("permission"), 12345).0,
+            AuthResult::Allowed
+        );
+        // ... and the PermissionService whatever PID it restarted under
+        assert_eq!(
+            check_authorization_with_reason(23, Some("permission"), 12345).0,
+            AuthResult::Allowed
+        );
     }
 
     #[test]
     fn test_system_process_reason() {
-        let (result, reason) = check_authorization_with_reason(5, 12345);
+        let (result, reason) = check_authorization_with_reason(1, None, 12345);
         assert_eq!(result, AuthResult::Allowed);
         assert_eq!(reason, AuthReason::SystemProcess);
     }
@@ -212,17 +238,30 @@ mod tests {
 
     #[test]
     fn test_desktop_process_allowed() {
-        // Desktop (PID 10) should be allowed
-        assert_eq!(check_user_authorization(10, 12345), AuthResult::Allowed);
+        // Desktop is allowed whatever its PID
+        for pid in [10, 11, 40] {
+            assert_eq!(
+                check_authorization_with_reason(pid, Some("desktop"), 12345).0,
+                AuthResult::Allowed
+            );
+        }
     }
 
     #[test]
     fn test_desktop_process_reason() {
-        let (result, reason) = check_authorization_with_reason(10, 12345);
+        let (result, reason) = check_authorization_with_reason(10, Some("desktop"), 12345);
         assert_eq!(result, AuthResult::Allowed);
         assert_eq!(reason, AuthReason::DesktopProcess);
     }
 
+    #[test]
+    fn test_event_bus_is_not_the_desktop() {
+        // EventBusService is spawned 9th at boot and gets PID 10
+        let (result, reason) = check_authorization_with_reason(10, Some("events"), 12345);
+        assert_eq!(result, AuthResult::Denied);
+        assert_eq!(reason, AuthReason::UntrustedProcess);
+    }
+
     // =========================================================================
     // Unknown process denial tests (FAIL-CLOSED)
     // =========================================================================
@@ -230,66 +269,56 @@ mod tests {
     #[test]
     fn test_unknown_process_denied() {
         // Unknown processes should be denied (fail-closed per Rule 4)
-        assert_eq!(check_user_authorization(11, 12345), AuthResult::Denied);
+        assert_eq!(check_user_authorization(10, 12345), AuthResult::Denied);
         assert_eq!(check_user_authorization(50, 12345), AuthResult::Denied);
         assert_eq!(check_user_authorization(100, 12345), AuthResult::Denied);
     }
 
     #[test]
     fn test_unknown_process_reason() {
-        let (result, reason) = check_authorization_with_reason(99, 12345);
+        let (result, reason) = check_authorization_with_reason(99, None, 12345);
         assert_eq!(result, AuthResult::Denied);
         assert_eq!(reason, AuthReason::UntrustedProcess);
     }
 
+    #[test]
+    fn test_other_services_denied() {
+        // Low PIDs and boot services carry no trust of their own
+        for (pid, name) in [(3, "vfs"), (5, "time"), (9, "terminal"), (12, "update")] {
+            let (result, reason) = check_authorization_with_reason(pid, Some(name), 12345);
+            assert_eq!(result, AuthResult::Denied);
+            assert_eq!(reason, AuthReason::UntrustedProcess);
+        }
+    }
+
     // =========================================================================
     // Helper function tests
     // =========================================================================
 
     #[test]
     fn test_is_system_process() {
-        assert!(is_system_process(0));
-        assert!(is_system_process(5));
-        assert!(is_system_process(9));
-        assert!(!is_system_process(10));
-        assert!(!is_system_process(11));
+        assert!(is_system_process(0, None));
+        assert!(is_system_process(1, None));
+        assert!(is_system_process(14, Some("permission")));
+        assert!(!is_system_process(2, None));
+        assert!(!is_system_process(5, Some("identity")));
+        assert!(!is_system_process(10, Some("desktop")));
     }
 
     #[test]
     fn test_is_desktop_process() {
-        assert!(!is_desktop_process(9));
-        assert!(is_desktop_process(10));
-        assert!(!is_desktop_process(11));
-    }
-
-    // =========================================================================
-    // Boundary tests
-    // =========================================================================
-
-    #[test]
-    fn test_boundary_between_system_and_desktop() {
-        // PID 9 is last system process
-        let (result9, reason9) = check_authorization_with_reason(9, 12345);
-        assert_eq!(result9, AuthResult::Allowed);
-        assert_eq!(reason9, AuthReason::SystemProcess);
-
-        // PID 10 is desktop
-        let (result10, reason10) = check_authorization_with_reason(10, 12345);
-        assert_eq!(result10, AuthResult::Allowed);
-        assert_eq!(reason10, AuthReason::DesktopProcess);
-
-        // PID 11 is first untrusted
-        let (result11, reason11) = check_authorization_with_reason(11, 12345);
-        assert_eq!(result11, AuthResult::Denied);
-        assert_eq!(reason11, AuthReason::UntrustedProcess);
+        assert!(!is_desktop_process(None));
+        assert!(is_desktop_process(Some("desktop")));
+        assert!(!is_desktop_process(Some("events")));
     }
 
     #[test]
     fn test_authorization_different_users() {
-        // Same PID should have same result regardless of target user
+        // Same caller should have same result regardless of target user
         // (until we implement session token verification)
-        assert_eq!(check_user_authorization(10, 0), AuthResult::Allowed);
-        assert_eq!(check_user_authorization(10, 12345), AuthResult::Allowed);
-        assert_eq!(check_user_authorization(10, u128::MAX), AuthResult::Allowed);
+        let desktop = |user| check_authorization_with_reason(10, Some("desktop"), user).0;
+        assert_eq!(desktop(0), AuthResult::Allowed);
+        assert_eq!(desktop(12345), AuthResult::Allowed);
+        assert_eq!(desktop(u128::MAX), AuthResult::Allowed);
     }
 }
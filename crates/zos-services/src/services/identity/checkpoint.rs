@@ -0,0 +1,276 @@
+//! Service state checkpointing
+//!
+//! IdentityService flows span several VFS, keystore and network round trips.
+//! If the service restarts halfway through, the pending maps are gone and the
+//! client would wait for a response that never comes. The service therefore
+//! checkpoints which clients are waiting on which response, and on restart
+//! answers each of them with an error they can retry on.
+//!
+//! The checkpoint is a system-only VFS file. It is read once the service
+//! has registered; nothing is written until that read completes.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zos_apps::syscall;
+use zos_apps::{AppError, Message, Restored, ServiceState};
+use zos_identity::error::{CredentialError, ZidError};
+use zos_identity::KeyError;
+use zos_process::{
+    identity_cred, identity_enroll, identity_key, identity_machine, identity_prefs, identity_query,
+    identity_zid,
+};
+
+use super::pending::PendingStorageOp;
+use super::response;
+use super::IdentityService;
+
+/// Why interrupted requests fail.
+const RESTARTED: &str = "identity service restarted";
+
+/// State saved across IdentityService restarts.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdentityState {
+    /// Client requests awaiting a response
+    pub interrupted: Vec<InterruptedRequest>,
+}
+
+impl ServiceState for IdentityState {
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// A client request that was in flight at the last checkpoint.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct InterruptedRequest {
+    pub pid: u32,
+    pub response_tag: u32,
+}
+
+impl IdentityService {
+    /// Whether any pending operation is waiting to answer `pid`.
+    pub fn has_pending_ops_for(&self, pid: u32) -> bool {
+        self.pending_vfs_ops
+            .values()
+            .any(|op| op.client_pid() == Some(pid))
+            || self
+                .pending_keystore_ops
+                .values()
+                .any(|op| op.client_pid() == pid)
+            || self
+                .pending_net_ops
+                .values()
+                .any(|op| op.client_pid() == pid)
+    }
+
+    /// Remember a request that is still in progress after its handler ran.
+    ///
+    /// Requests answered synchronously leave nothing pending and are not
+    /// tracked. Every identity response tag is the request tag plus one.
+    pub fn track_client_request(&mut self, msg: &Message) {
+        if self.has_pending_ops_for(msg.from_pid) {
+            self.client_requests.insert(InterruptedRequest {
+                pid: msg.from_pid,
+                response_tag: msg.tag + 1,
+            });
+        }
+    }
+
+    /// Read the checkpoint left by a previous run.
+    pub fn start_state_restore(&mut self) {
+        self.restore_requested = true;
+        let path = self.checkpoint.location();
+        if self
+            .start_vfs_read(&path, PendingStorageOp::RestoreState)
+            .is_err()
+        {
+            let _ = self.finish_state_restore(Err(String::from("VFS unavailable")));
+        }
+    }
+
+    /// Checkpoint read completed (a missing file reads as an error).
+    pub fn finish_state_restore(
+        &mut self,
+        result: Result<Vec<u8>, String>,
+    ) -> Result<(), AppError> {
+        let outcome = match result {
+            Ok(data) => self.checkpoint.restore(Some(&data)),
+            Err(e) => {
+                syscall::debug(&format!("IdentityService: No checkpoint restored ({})", e));
+                self.checkpoint.restore(None)
+            }
+        };
+        syscall::debug(&format!("IdentityService: State restore: {:?}", outcome));
+
+        if outcome != Restored::Fresh {
+            for request in &self.checkpoint.state().interrupted {
+                fail_interrupted_request(request);
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a checkpoint if the tracked requests changed and one is due.
+    pub fn pump_checkpoint(&mut self, now_ms: u64) {
+        if !self.checkpoint.is_restored() {
+            return;
+        }
+        // Forget requests that have been answered
+        let requests = core::mem::take(&mut self.client_requests);
+        self.client_requests = requests
+            .into_iter()
+            .filter(|r| self.has_pending_ops_for(r.pid))
+            .collect();
+        let snapshot = IdentityState {
+            interrupted: self.client_requests.iter().cloned().collect(),
+        };
+        if *self.checkpoint.state() != snapshot {
+            *self.checkpoint.state_mut() = snapshot;
+        }
+        if !self.checkpoint.checkpoint_due(now_ms) {
+            return;
+        }
+
+        let data = match self.checkpoint.begin_checkpoint(now_ms) {
+            Ok(data) => data,
+            Err(e) => {
+                syscall::debug(&format!("IdentityService: {}", e));
+                return;
+            }
+        };
+        let path = self.checkpoint.location();
+        if self
+            .start_vfs_write(&path, &data, PendingStorageOp::CheckpointState)
+            .is_err()
+        {
+            self.checkpoint.finish_checkpoint(false);
+        }
+    }
+
+    /// Checkpoint write completed.
+    pub fn finish_state_checkpoint(&mut self, result: Result<(), String>) -> Result<(), AppError> {
+        if let Err(ref e) = result {
+            syscall::debug(&format!("IdentityService: Checkpoint write failed: {}", e));
+        }
+        self.checkpoint.finish_checkpoint(result.is_ok());
+        Ok(())
+    }
+}
+
+/// Answer a request lost in a restart with the error form of its response.
+fn fail_interrupted_request(request: &InterruptedRequest) {
+    syscall::debug(&format!(
+        "IdentityService: Failing request interrupted by restart (PID {}, tag 0x{:x})",
+        request.pid, request.response_tag
+    ));
+    if let Err(e) = send_error_response(request.pid, &[], request.response_tag, RESTARTED) {
+        syscall::debug(&format!(
+            "IdentityService: Could not fail interrupted request: {}",
+            e
+        ));
+    }
+}
+
+/// Send the error form of the response `response_tag`, with `reason` as
+/// the error message.
+pub(super) fn send_error_response(
+    pid: u32,
+    cap_slots: &[u32],
+    response_tag: u32,
+    reason: &str,
+) -> Result<(), AppError> {
+    let key_error = || KeyError::StorageError(reason.into());
+    let cred_error = || CredentialError::StorageError(reason.into());
+    let zid_error = || ZidError::ServerError(reason.into());
+    match response_tag {
+        identity_key::MSG_GENERATE_NEURAL_KEY_RESPONSE => {
+            response::send_neural_key_error(pid, cap_slots, key_error())
+        }
+        identity_key::MSG_RECOVER_NEURAL_KEY_RESPONSE => {
+            response::send_recover_key_error(pid, cap_slots, key_error())
+        }
+        identity_key::MSG_GET_IDENTITY_KEY_RESPONSE => {
+            response::send_get_identity_key_error(pid, cap_slots, key_error())
+        }
+        identity_query::MSG_GET_KEY_FINGERPRINT_RESPONSE => {
+            response::send_get_key_fingerprint_error(pid, cap_slots, key_error())
+        }
+        identity_key::MSG_EXPORT_SHARD_RESPONSE => {
+            response::send_export_shard_error(pid, cap_slots, key_error())
+        }
+        identity_key::MSG_VERIFY_SHARDS_RESPONSE => {
+            response::send_verify_shards_error(pid, cap_slots, key_error())
+        }
+        identity_key::MSG_MARK_SHARD_BACKED_UP_RESPONSE => {
+            response::send_mark_shard_backed_up_error(pid, cap_slots, key_error())
+        }
+        identity_key::MSG_GET_SHARD_BACKUP_STATUS_RESPONSE => {
+            response::send_shard_backup_status_error(pid, cap_slots, key_error())
+        }
+        identity_machine::MSG_CREATE_MACHINE_KEY_RESPONSE => {
+            response::send_create_machine_key_error(pid, cap_slots, key_error())
+        }
+        identity_machine::MSG_LIST_MACHINE_KEYS_RESPONSE => {
+            response::send_list_machine_keys_error(pid, cap_slots, key_error())
+        }
+        identity_machine::MSG_GET_MACHINE_KEY_RESPONSE => {
+            response::send_get_machine_key_error(pid, cap_slots, key_error())
+        }
+        identity_machine::MSG_REVOKE_MACHINE_KEY_RESPONSE => {
+            response::send_revoke_machine_key_error(pid, cap_slots, key_error())
+        }
+        identity_machine::MSG_ROTATE_MACHINE_KEY_RESPONSE => {
+            response::send_rotate_machine_key_error(pid, cap_slots, key_error())
+        }
+        identity_machine::MSG_CREATE_MACHINE_KEY_AND_ENROLL_RESPONSE => {
+            response::send_create_machine_key_and_enroll_error(pid, cap_slots, zid_error())
+        }
+        identity_enroll::MSG_REQUEST_ENROLLMENT_RESPONSE => {
+            response::send_request_enrollment_error(pid, cap_slots, zid_error())
+        }
+        identity_enroll::MSG_LIST_PENDING_ENROLLMENTS_RESPONSE => {
+            response::send_list_pending_enrollments_error(pid, cap_slots, zid_error())
+        }
+        identity_enroll::MSG_DECIDE_ENROLLMENT_RESPONSE => {
+            response::send_decide_enrollment_error(pid, cap_slots, zid_error())
+        }
+        identity_enroll::MSG_COMPLETE_ENROLLMENT_RESPONSE => {
+            response::send_complete_enrollment_error(pid, cap_slots, zid_error())
+        }
+        identity_cred::MSG_ATTACH_EMAIL_RESPONSE => {
+            response::send_attach_email_error(pid, cap_slots, cred_error())
+        }
+        identity_cred::MSG_GET_CREDENTIALS_RESPONSE => {
+            response::send_get_credentials_error(pid, cap_slots, cred_error())
+        }
+        identity_cred::MSG_UNLINK_CREDENTIAL_RESPONSE => {
+            response::send_unlink_credential_error(pid, cap_slots, cred_error())
+        }
+        identity_zid::MSG_ZID_LOGIN_RESPONSE => {
+            response::send_zid_login_error(pid, cap_slots, zid_error())
+        }
+        identity_zid::MSG_ZID_LOGIN_EMAIL_RESPONSE => {
+            response::send_zid_email_login_error(pid, cap_slots, zid_error())
+        }
+        identity_zid::MSG_ZID_ENROLL_MACHINE_RESPONSE => {
+            response::send_zid_enroll_error(pid, cap_slots, zid_error())
+        }
+        identity_zid::MSG_ZID_LOGOUT_RESPONSE => {
+            response::send_zid_logout_error(pid, cap_slots, zid_error())
+        }
+        identity_zid::MSG_ZID_REFRESH_RESPONSE => {
+            response::send_zid_refresh_error(pid, cap_slots, zid_error())
+        }
+        identity_prefs::MSG_SET_DEFAULT_KEY_SCHEME_RESPONSE => {
+            response::send_set_default_key_scheme_error(pid, cap_slots, key_error())
+        }
+        identity_prefs::MSG_SET_DEFAULT_MACHINE_KEY_RESPONSE => {
+            response::send_set_default_machine_key_error(pid, cap_slots, key_error())
+        }
+        identity_prefs::MSG_SET_LOGIN_ITEMS_RESPONSE => {
+            response::send_set_login_items_error(pid, cap_slots, key_error())
+        }
+        // Preference reads have no error response
+        _ => Ok(()),
+    }
+}
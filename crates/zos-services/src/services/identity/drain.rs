@@ -0,0 +1,91 @@
+//! Graceful drain
+//!
+//! Once init sends `MSG_SERVICE_DRAIN`, new client requests are answered
+//! with an error asking the client to retry, while flows already under way
+//! run to completion (including their VFS writes). When nothing is pending
+//! the client list is checkpointed one last time and the drain is acked.
+
+use alloc::format;
+use zos_apps::syscall;
+use zos_apps::{AppError, DrainStep, Message};
+use zos_process::{identity_prefs, net};
+
+use super::checkpoint::send_error_response;
+use super::pending::PendingStorageOp;
+use super::IdentityService;
+
+/// Why requests are refused while draining.
+const DRAINING: &str = "identity service is restarting, retry shortly";
+
+impl IdentityService {
+    /// Handle MSG_SERVICE_DRAIN from init.
+    pub fn handle_drain(&mut self, msg: &Message) -> Result<(), AppError> {
+        if msg.from_pid != zos_process::pid::INIT {
+            syscall::debug(&format!(
+                "IdentityService: Ignoring drain request from non-init PID {}",
+                msg.from_pid
+            ));
+            return Ok(());
+        }
+        if self.drain.start() {
+            syscall::debug(&format!(
+                "IdentityService: Draining ({} VFS, {} keystore, {} network ops pending)",
+                self.pending_vfs_ops.len(),
+                self.pending_keystore_ops.len(),
+                self.pending_net_ops.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether `msg` should be refused rather than handled.
+    ///
+    /// Network results belong to flows already under way. Preference reads
+    /// have no error response, so they are still served.
+    pub fn refuses_while_draining(&self, tag: u32) -> bool {
+        self.drain.is_draining()
+            && tag != net::MSG_NET_RESULT
+            && tag != identity_prefs::MSG_GET_IDENTITY_PREFERENCES
+    }
+
+    /// Refuse a client request because the service is draining.
+    pub fn refuse_while_draining(&self, msg: &Message) -> Result<(), AppError> {
+        syscall::debug(&format!(
+            "IdentityService: Draining, refusing tag 0x{:x} from PID {}",
+            msg.tag, msg.from_pid
+        ));
+        send_error_response(msg.from_pid, &msg.cap_slots, msg.tag + 1, DRAINING)
+    }
+
+    /// Advance the drain: final checkpoint once idle, then ack.
+    pub fn pump_drain(&mut self, now_ms: u64) {
+        if !self.drain.is_draining() {
+            return;
+        }
+        let idle = self.pending_keystore_ops.is_empty()
+            && self.pending_net_ops.is_empty()
+            && self
+                .pending_vfs_ops
+                .values()
+                .all(|op| matches!(op, PendingStorageOp::CheckpointState));
+        match self.drain.poll(idle, &mut self.checkpoint, now_ms) {
+            Ok(DrainStep::Wait) => {}
+            Ok(DrainStep::Checkpoint(data)) => {
+                let path = self.checkpoint.location();
+                if self
+                    .start_vfs_write(&path, &data, PendingStorageOp::CheckpointState)
+                    .is_err()
+                {
+                    self.checkpoint.finish_checkpoint(false);
+                }
+            }
+            Ok(DrainStep::Ack) => {
+                syscall::debug("IdentityService: Drained, ready for restart");
+                if let Err(e) = self.drain.ack() {
+                    syscall::debug(&format!("IdentityService: {}", e));
+                }
+            }
+            Err(e) => syscall::debug(&format!("IdentityService: {}", e)),
+        }
+    }
+}
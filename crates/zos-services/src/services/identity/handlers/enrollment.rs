@@ -0,0 +1,671 @@
+//! Machine enrollment approvals
+//!
+//! Handlers for joining a machine to an existing identity:
+//! - Request enrollment (new machine): keypair generated, seeds kept in the
+//!   keystore, request posted to ZID
+//! - List pending enrollments (enrolled machine)
+//! - Decide enrollment (enrolled machine): approval or rejection signed with
+//!   the approver's machine key
+//! - Complete enrollment (new machine): decision verified, machine key stored
+//!
+//! `MSG_ZID_ENROLL_MACHINE` registers a brand-new identity with its first
+//! machine. Every further machine goes through this handshake instead, so a
+//! machine can never add itself to an identity.
+//!
+//! # Safety Invariants (per zos-service.md Rule 0)
+//!
+//! ## Success Conditions
+//! - Request: seeds stored in keystore, request accepted by ZID, request returned
+//! - Decide: approver key may authorize machines, signed decision accepted by ZID
+//! - Complete: decision verified against this request's keys, machine key stored
+//!
+//! ## Acceptable Partial Failure
+//! - Request record left in keystore when ZID refuses the submission (it expires)
+//! - Request record left after the machine key is stored (completing again
+//!   rewrites the same key)
+//!
+//! ## Forbidden States
+//! - Storing a machine key without a verified approval
+//! - Signing a decision with a key that cannot authorize machines
+//! - Acting on an expired request
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::Deserialize;
+
+use super::super::network::parse_zid_error_response;
+use super::super::pending::{PendingKeystoreOp, PendingNetworkOp, RequestContext};
+use super::super::response;
+use super::super::utils::{
+    bytes_to_hex, hex_to_bytes, machine_keypair_from_seeds, sign_with_machine_keypair,
+};
+use super::super::{check_user_authorization, log_denial, AuthResult, IdentityService};
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_identity::crypto::NeuralKey;
+use zos_identity::error::ZidError;
+use zos_identity::ipc::{
+    CompleteEnrollmentRequest, DecideEnrollmentRequest, EnrollmentDecision, EnrollmentOutcome,
+    EnrollmentStatus, ListPendingEnrollmentsRequest, LocalEnrollment, PendingEnrollment,
+    RequestEnrollmentRequest, ENROLLMENT_TTL_MS,
+};
+use zos_identity::keystore::MachineKeyRecord;
+use zos_network::{HttpRequest, HttpResponse};
+
+// =============================================================================
+// Request Enrollment (new machine)
+// =============================================================================
+
+pub fn handle_request_enrollment(
+    service: &mut IdentityService,
+    msg: &Message,
+) -> Result<(), AppError> {
+    // Rule 1: Parse request - return InvalidRequest on parse failure
+    let request: RequestEnrollmentRequest = match serde_json::from_slice(&msg.data) {
+        Ok(r) => r,
+        Err(e) => {
+            syscall::debug(&format!("IdentityService: Failed to parse request: {}", e));
+            return response::send_request_enrollment_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                ZidError::InvalidRequest(format!("JSON parse error: {}", e)),
+            );
+        }
+    };
+
+    // Rule 4: Authorization check (FAIL-CLOSED)
+    if check_user_authorization(msg.from_pid, request.user_id) == AuthResult::Denied {
+        log_denial("request_enrollment", msg.from_pid, request.user_id);
+        return response::send_request_enrollment_error(
+            msg.from_pid,
+            &msg.cap_slots,
+            ZidError::Unauthorized,
+        );
+    }
+
+    // Fresh random IDs and seeds; nothing is derived from the Neural Key,
+    // which this machine does not have
+    let (ids, signing_sk, encryption_sk) = match (
+        NeuralKey::generate(),
+        NeuralKey::generate(),
+        NeuralKey::generate(),
+    ) {
+        (Ok(ids), Ok(signing), Ok(encryption)) => {
+            (*ids.as_bytes(), *signing.as_bytes(), *encryption.as_bytes())
+        }
+        _ => {
+            return response::send_request_enrollment_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                ZidError::EnrollmentFailed("Failed to generate machine key".into()),
+            );
+        }
+    };
+    let keypair = match machine_keypair_from_seeds(&signing_sk, &encryption_sk) {
+        Ok(keypair) => keypair,
+        Err(e) => {
+            return response::send_request_enrollment_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                ZidError::EnrollmentFailed(format!("{:?}", e)),
+            );
+        }
+    };
+
+    let mut request_id = [0u8; 16];
+    let mut machine_id = [0u8; 16];
+    request_id.copy_from_slice(&ids[..16]);
+    machine_id.copy_from_slice(&ids[16..]);
+    let now = syscall::get_wallclock();
+    let local = LocalEnrollment {
+        enrollment: PendingEnrollment {
+            request_id: u128::from_le_bytes(request_id),
+            machine_id: u128::from_le_bytes(machine_id),
+            signing_public_key: keypair.signing_public_key(),
+            encryption_public_key: keypair.encryption_public_key(),
+            machine_name: request.machine_name,
+            capabilities: request.capabilities,
+            created_at: now,
+            expires_at: now + ENROLLMENT_TTL_MS,
+        },
+        zid_endpoint: request.zid_endpoint,
+        signing_sk,
+        encryption_sk,
+    };
+
+    // Seeds are stored before the request is published, so an approval can
+    // never arrive for a key this machine has lost
+    let json_bytes = match serde_json::to_vec(&local) {
+        Ok(b) => b,
+        Err(e) => {
+            return response::send_request_enrollment_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                ZidError::EnrollmentFailed(format!("Serialization failed: {}", e)),
+            );
+        }
+    };
+    let path = LocalEnrollment::storage_path(request.user_id, local.enrollment.request_id);
+    let ctx = RequestContext::new(msg.from_pid, msg.cap_slots.clone());
+    service.start_keystore_write(
+        &path,
+        &json_bytes,
+        PendingKeystoreOp::WriteEnrollmentRequest {
+            ctx,
+            local: Box::new(local),
+        },
+    )
+}
+
+pub fn continue_request_enrollment_after_write(
+    service: &mut IdentityService,
+    ctx: RequestContext,
+    local: LocalEnrollment,
+    result: Result<(), String>,
+) -> Result<(), AppError> {
+    if let Err(e) = result {
+        return response::send_request_enrollment_error(
+            ctx.client_pid,
+            &ctx.cap_slots,
+            ZidError::EnrollmentFailed(format!("Keystore write failed: {}", e)),
+        );
+    }
+
+    let body = match serde_json::to_vec(&local.enrollment) {
+        Ok(b) => b,
+        Err(e) => {
+            return response::send_request_enrollment_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                ZidError::EnrollmentFailed(format!("Serialization failed: {}", e)),
+            );
+        }
+    };
+    let request = HttpRequest::post(format!("{}/v1/machines/enrollments", local.zid_endpoint))
+        .with_json_body(body)
+        .with_timeout(10_000);
+    service.start_network_fetch(
+        &request,
+        PendingNetworkOp::SubmitEnrollmentRequest {
+            ctx,
+            enrollment: Box::new(local.enrollment),
+        },
+    )
+}
+
+pub fn continue_request_enrollment_after_submit(
+    ctx: RequestContext,
+    enrollment: PendingEnrollment,
+    http_response: HttpResponse,
+) -> Result<(), AppError> {
+    let result = zid_result(http_response).map(|_| {
+        syscall::debug(&format!(
+            "IdentityService: Enrollment request {:032x} submitted, awaiting approval",
+            enrollment.request_id
+        ));
+        enrollment
+    });
+    response::send_request_enrollment_response(ctx.client_pid, &ctx.cap_slots, result)
+}
+
+// =============================================================================
+// List Pending Enrollments (enrolled machine)
+// =============================================================================
+
+pub fn handle_list_pending_enrollments(
+    service: &mut IdentityService,
+    msg: &Message,
+) -> Result<(), AppError> {
+    // Rule 1: Parse request - return InvalidRequest on parse failure
+    let request: ListPendingEnrollmentsRequest = match serde_json::from_slice(&msg.data) {
+        Ok(r) => r,
+        Err(e) => {
+            syscall::debug(&format!("IdentityService: Failed to parse request: {}", e));
+            return response::send_list_pending_enrollments_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                ZidError::InvalidRequest(format!("JSON parse error: {}", e)),
+            );
+        }
+    };
+
+    // Rule 4: Authorization check (FAIL-CLOSED)
+    if check_user_authorization(msg.from_pid, request.user_id) == AuthResult::Denied {
+        log_denial("list_pending_enrollments", msg.from_pid, request.user_id);
+        return response::send_list_pending_enrollments_error(
+            msg.from_pid,
+            &msg.cap_slots,
+            ZidError::Unauthorized,
+        );
+    }
+
+    let http_request =
+        HttpRequest::get(format!("{}/v1/machines/enrollments", request.zid_endpoint))
+            .with_bearer_token(request.access_token)
+            .with_timeout(10_000);
+    let ctx = RequestContext::new(msg.from_pid, msg.cap_slots.clone());
+    service.start_network_fetch(
+        &http_request,
+        PendingNetworkOp::ListPendingEnrollments { ctx },
+    )
+}
+
+pub fn continue_list_pending_enrollments(
+    ctx: RequestContext,
+    http_response: HttpResponse,
+) -> Result<(), AppError> {
+    #[derive(Deserialize)]
+    struct EnrollmentList {
+        enrollments: Vec<PendingEnrollment>,
+    }
+
+    let result = zid_result(http_response).and_then(|body| {
+        serde_json::from_slice::<EnrollmentList>(&body)
+            .map_err(|e| ZidError::ServerError(format!("Invalid enrollment list: {}", e)))
+    });
+    let now = syscall::get_wallclock();
+    let result = result.map(|list| {
+        list.enrollments
+            .into_iter()
+            .filter(|enrollment| !enrollment.is_expired(now))
+            .collect()
+    });
+    response::send_list_pending_enrollments_response(ctx.client_pid, &ctx.cap_slots, result)
+}
+
+// =============================================================================
+// Decide Enrollment (enrolled machine)
+// =============================================================================
+
+pub fn handle_decide_enrollment(
+    service: &mut IdentityService,
+    msg: &Message,
+) -> Result<(), AppError> {
+    // Rule 1: Parse request - return InvalidRequest on parse failure
+    let request: DecideEnrollmentRequest = match serde_json::from_slice(&msg.data) {
+        Ok(r) => r,
+        Err(e) => {
+            syscall::debug(&format!("IdentityService: Failed to parse request: {}", e));
+            return response::send_decide_enrollment_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                ZidError::InvalidRequest(format!("JSON parse error: {}", e)),
+            );
+        }
+    };
+
+    // Rule 4: Authorization check (FAIL-CLOSED)
+    if check_user_authorization(msg.from_pid, request.user_id) == AuthResult::Denied {
+        log_denial("decide_enrollment", msg.from_pid, request.user_id);
+        return response::send_decide_enrollment_error(
+            msg.from_pid,
+            &msg.cap_slots,
+            ZidError::Unauthorized,
+        );
+    }
+
+    if request.enrollment.is_expired(syscall::get_wallclock()) {
+        return response::send_decide_enrollment_error(
+            msg.from_pid,
+            &msg.cap_slots,
+            ZidError::EnrollmentExpired,
+        );
+    }
+
+    let path = MachineKeyRecord::storage_path(request.user_id, request.approver_machine_id);
+    let ctx = RequestContext::new(msg.from_pid, msg.cap_slots.clone());
+    service.start_keystore_read(
+        &path,
+        PendingKeystoreOp::ReadMachineKeyForEnrollmentDecision {
+            ctx,
+            zid_endpoint: request.zid_endpoint,
+            enrollment: Box::new(request.enrollment),
+            outcome: request.outcome,
+        },
+    )
+}
+
+pub fn continue_decide_enrollment_after_read(
+    service: &mut IdentityService,
+    ctx: RequestContext,
+    zid_endpoint: String,
+    enrollment: PendingEnrollment,
+    outcome: EnrollmentOutcome,
+    data: &[u8],
+) -> Result<(), AppError> {
+    let approver: MachineKeyRecord = match serde_json::from_slice(data) {
+        Ok(r) => r,
+        Err(_) => {
+            return response::send_decide_enrollment_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                ZidError::MachineKeyNotFound,
+            );
+        }
+    };
+
+    let now = syscall::get_wallclock();
+    let seeds = approver.signing_sk.zip(approver.encryption_sk);
+    let (signing_sk, encryption_sk) = match seeds {
+        Some(seeds)
+            if approver.capabilities.can_authorize_machines()
+                && !approver.capabilities.is_expired(now) =>
+        {
+            seeds
+        }
+        _ => {
+            syscall::debug(&format!(
+                "IdentityService: Machine {:032x} cannot authorize machines",
+                approver.machine_id
+            ));
+            return response::send_decide_enrollment_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                ZidError::Unauthorized,
+            );
+        }
+    };
+    let keypair = match machine_keypair_from_seeds(&signing_sk, &encryption_sk) {
+        Ok(keypair) => keypair,
+        Err(e) => {
+            return response::send_decide_enrollment_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                ZidError::EnrollmentFailed(format!("{:?}", e)),
+            );
+        }
+    };
+
+    let message = enrollment.decision_message(outcome, approver.machine_id, now);
+    let decision = EnrollmentDecision {
+        request_id: enrollment.request_id,
+        outcome,
+        approver_machine_id: approver.machine_id,
+        approver_signing_public_key: approver.signing_public_key,
+        decided_at: now,
+        signature: bytes_to_hex(&sign_with_machine_keypair(&message, &keypair)),
+    };
+    let body = match serde_json::to_vec(&decision) {
+        Ok(b) => b,
+        Err(e) => {
+            return response::send_decide_enrollment_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                ZidError::EnrollmentFailed(format!("Serialization failed: {}", e)),
+            );
+        }
+    };
+
+    syscall::debug(&format!(
+        "IdentityService: Machine {:032x} signed {:?} for enrollment {:032x}",
+        approver.machine_id, outcome, enrollment.request_id
+    ));
+    let request = HttpRequest::post(format!(
+        "{}/v1/machines/enrollments/{:032x}/decision",
+        zid_endpoint, enrollment.request_id
+    ))
+    .with_json_body(body)
+    .with_timeout(10_000);
+    service.start_network_fetch(&request, PendingNetworkOp::SubmitEnrollmentDecision { ctx })
+}
+
+pub fn continue_decide_enrollment_after_submit(
+    ctx: RequestContext,
+    http_response: HttpResponse,
+) -> Result<(), AppError> {
+    let result = zid_result(http_response).map(|_| ());
+    response::send_decide_enrollment_response(ctx.client_pid, &ctx.cap_slots, result)
+}
+
+// =============================================================================
+// Complete Enrollment (new machine)
+// =============================================================================
+
+pub fn handle_complete_enrollment(
+    service: &mut IdentityService,
+    msg: &Message,
+) -> Result<(), AppError> {
+    // Rule 1: Parse request - return InvalidRequest on parse failure
+    let request: CompleteEnrollmentRequest = match serde_json::from_slice(&msg.data) {
+        Ok(r) => r,
+        Err(e) => {
+            syscall::debug(&format!("IdentityService: Failed to parse request: {}", e));
+            return response::send_complete_enrollment_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                ZidError::InvalidRequest(format!("JSON parse error: {}", e)),
+            );
+        }
+    };
+
+    // Rule 4: Authorization check (FAIL-CLOSED)
+    if check_user_authorization(msg.from_pid, request.user_id) == AuthResult::Denied {
+        log_denial("complete_enrollment", msg.from_pid, request.user_id);
+        return response::send_complete_enrollment_error(
+            msg.from_pid,
+            &msg.cap_slots,
+            ZidError::Unauthorized,
+        );
+    }
+
+    let path = LocalEnrollment::storage_path(request.user_id, request.request_id);
+    let ctx = RequestContext::new(msg.from_pid, msg.cap_slots.clone());
+    service.start_keystore_read(
+        &path,
+        PendingKeystoreOp::ReadEnrollmentForCompletion {
+            ctx,
+            user_id: request.user_id,
+        },
+    )
+}
+
+pub fn continue_complete_enrollment_after_read(
+    service: &mut IdentityService,
+    ctx: RequestContext,
+    user_id: u128,
+    data: &[u8],
+) -> Result<(), AppError> {
+    let local: LocalEnrollment = match serde_json::from_slice(data) {
+        Ok(local) => local,
+        Err(_) => {
+            return response::send_complete_enrollment_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                ZidError::EnrollmentNotFound,
+            );
+        }
+    };
+
+    if local.enrollment.is_expired(syscall::get_wallclock()) {
+        return discard_enrollment(service, ctx, user_id, &local, ZidError::EnrollmentExpired);
+    }
+
+    let request = HttpRequest::get(format!(
+        "{}/v1/machines/enrollments/{:032x}",
+        local.zid_endpoint, local.enrollment.request_id
+    ))
+    .with_timeout(10_000);
+    service.start_network_fetch(
+        &request,
+        PendingNetworkOp::FetchEnrollmentStatus {
+            ctx,
+            user_id,
+            local: Box::new(local),
+        },
+    )
+}
+
+pub fn continue_complete_enrollment_after_status(
+    service: &mut IdentityService,
+    ctx: RequestContext,
+    user_id: u128,
+    local: LocalEnrollment,
+    http_response: HttpResponse,
+) -> Result<(), AppError> {
+    let status = zid_result(http_response).and_then(|body| {
+        serde_json::from_slice::<EnrollmentStatus>(&body)
+            .map_err(|e| ZidError::ServerError(format!("Invalid enrollment status: {}", e)))
+    });
+    let decision = match status {
+        Ok(EnrollmentStatus::Decided { decision }) => decision,
+        Ok(EnrollmentStatus::Pending) => {
+            return response::send_complete_enrollment_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                ZidError::EnrollmentPending,
+            );
+        }
+        Ok(EnrollmentStatus::Expired) => {
+            return discard_enrollment(service, ctx, user_id, &local, ZidError::EnrollmentExpired);
+        }
+        Err(e) => {
+            return response::send_complete_enrollment_error(ctx.client_pid, &ctx.cap_slots, e);
+        }
+    };
+
+    if let Err(e) = verify_decision(&local.enrollment, &decision) {
+        syscall::debug(&format!(
+            "IdentityService: Rejecting unverifiable decision on enrollment {:032x}",
+            local.enrollment.request_id
+        ));
+        return response::send_complete_enrollment_error(ctx.client_pid, &ctx.cap_slots, e);
+    }
+    if decision.outcome == EnrollmentOutcome::Reject {
+        return discard_enrollment(service, ctx, user_id, &local, ZidError::EnrollmentRejected);
+    }
+
+    let request_id = local.enrollment.request_id;
+    let record = local.into_machine_key(&decision);
+    let json_bytes = match serde_json::to_vec(&record) {
+        Ok(b) => b,
+        Err(e) => {
+            return response::send_complete_enrollment_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                ZidError::EnrollmentFailed(format!("Serialization failed: {}", e)),
+            );
+        }
+    };
+    syscall::debug(&format!(
+        "IdentityService: Enrollment {:032x} approved by machine {:032x}",
+        request_id, decision.approver_machine_id
+    ));
+    let path = MachineKeyRecord::storage_path(user_id, record.machine_id);
+    service.start_keystore_write(
+        &path,
+        &json_bytes,
+        PendingKeystoreOp::WriteEnrolledMachineKey {
+            ctx,
+            user_id,
+            request_id,
+            record: Box::new(record),
+        },
+    )
+}
+
+pub fn continue_complete_enrollment_after_write(
+    service: &mut IdentityService,
+    ctx: RequestContext,
+    user_id: u128,
+    request_id: u128,
+    record: MachineKeyRecord,
+    result: Result<(), String>,
+) -> Result<(), AppError> {
+    if let Err(e) = result {
+        // The request is kept, so completing again can retry the write
+        return response::send_complete_enrollment_error(
+            ctx.client_pid,
+            &ctx.cap_slots,
+            ZidError::EnrollmentFailed(format!("Keystore write failed: {}", e)),
+        );
+    }
+    let path = LocalEnrollment::storage_path(user_id, request_id);
+    service.start_keystore_delete(
+        &path,
+        PendingKeystoreOp::DeleteEnrollment {
+            ctx,
+            outcome: Ok(Box::new(record)),
+        },
+    )
+}
+
+/// Request record deleted (or not); send the response it was holding.
+pub fn finish_enrollment_after_delete(
+    ctx: RequestContext,
+    outcome: Result<Box<MachineKeyRecord>, ZidError>,
+    result: Result<(), String>,
+) -> Result<(), AppError> {
+    if let Err(e) = result {
+        syscall::debug(&format!(
+            "IdentityService: Failed to delete enrollment request: {}",
+            e
+        ));
+    }
+    response::send_complete_enrollment_response(
+        ctx.client_pid,
+        &ctx.cap_slots,
+        outcome.map(|record| *record),
+    )
+}
+
+// =============================================================================
+// Helpers
+// =============================================================================
+
+/// Delete a request that can no longer be approved, then answer `error`.
+fn discard_enrollment(
+    service: &mut IdentityService,
+    ctx: RequestContext,
+    user_id: u128,
+    local: &LocalEnrollment,
+    error: ZidError,
+) -> Result<(), AppError> {
+    let path = LocalEnrollment::storage_path(user_id, local.enrollment.request_id);
+    service.start_keystore_delete(
+        &path,
+        PendingKeystoreOp::DeleteEnrollment {
+            ctx,
+            outcome: Err(error),
+        },
+    )
+}
+
+/// Check a decision relayed by ZID was signed for exactly this request.
+///
+/// ZID vouches that the approver is an enrolled machine of the identity;
+/// the signature stops it from swapping the keys being approved.
+pub fn verify_decision(
+    enrollment: &PendingEnrollment,
+    decision: &EnrollmentDecision,
+) -> Result<(), ZidError> {
+    if decision.request_id != enrollment.request_id {
+        return Err(ZidError::AuthenticationFailed);
+    }
+    let key = VerifyingKey::from_bytes(&decision.approver_signing_public_key)
+        .map_err(|_| ZidError::AuthenticationFailed)?;
+    let signature: [u8; 64] = hex_to_bytes(&decision.signature)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ZidError::AuthenticationFailed)?;
+    let message = enrollment.decision_message(
+        decision.outcome,
+        decision.approver_machine_id,
+        decision.decided_at,
+    );
+    key.verify_strict(&message, &Signature::from_bytes(&signature))
+        .map_err(|_| ZidError::AuthenticationFailed)
+}
+
+/// Body of a successful ZID response, or the error it carries.
+fn zid_result(http_response: HttpResponse) -> Result<Vec<u8>, ZidError> {
+    match http_response.result {
+        Ok(success) if (200..300).contains(&success.status) => Ok(success.body),
+        Ok(success) if success.status == 401 => Err(ZidError::SessionExpired),
+        Ok(success) if success.status == 404 => Err(ZidError::EnrollmentNotFound),
+        Ok(success) if success.status == 410 => Err(ZidError::EnrollmentExpired),
+        Ok(success) => Err(parse_zid_error_response(&success.body, success.status)),
+        Err(e) => Err(ZidError::NetworkError(e.message().into())),
+    }
+}
@@ -0,0 +1,275 @@
+//! Neural key shard backup handlers
+//!
+//! Support the key-backup flow for the 3 external shards handed out at
+//! generation:
+//! - Export a shard as a QR-encodable blob
+//! - Verify a shard set reaches the recovery threshold (no reconstruction)
+//! - Record and report which shards the user has backed up
+//!
+//! Shards are checked against the commitments stored in the
+//! `EncryptedShardStore` at generation time; the Neural Key itself is never
+//! rebuilt here.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::services::identity::pending::{PendingKeystoreOp, RequestContext, ShardBackupAction};
+use crate::services::identity::response;
+use crate::services::identity::{check_user_authorization, log_denial, AuthResult, IdentityService};
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_identity::backup::{
+    check_shard, check_shard_set, encode_shard_blob, ShardBackupState, ShardBackupStatus,
+};
+use zos_identity::ipc::{
+    ExportShardRequest, GetShardBackupStatusRequest, MarkShardBackedUpRequest,
+    VerifyShardsRequest,
+};
+use zos_identity::keystore::EncryptedShardStore;
+use zos_identity::KeyError;
+
+pub fn handle_export_shard(service: &mut IdentityService, msg: &Message) -> Result<(), AppError> {
+    let request: ExportShardRequest = match serde_json::from_slice(&msg.data) {
+        Ok(r) => r,
+        Err(e) => {
+            return response::send_export_shard_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                KeyError::InvalidRequest(format!("JSON parse error: {}", e)),
+            );
+        }
+    };
+    let action = ShardBackupAction::Export(request.shard);
+    read_shard_store(service, msg, "export_shard", request.user_id, action)
+}
+
+pub fn handle_verify_shards(service: &mut IdentityService, msg: &Message) -> Result<(), AppError> {
+    let request: VerifyShardsRequest = match serde_json::from_slice(&msg.data) {
+        Ok(r) => r,
+        Err(e) => {
+            return response::send_verify_shards_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                KeyError::InvalidRequest(format!("JSON parse error: {}", e)),
+            );
+        }
+    };
+    let action = ShardBackupAction::Verify(request.shards);
+    read_shard_store(service, msg, "verify_shards", request.user_id, action)
+}
+
+pub fn handle_mark_shard_backed_up(
+    service: &mut IdentityService,
+    msg: &Message,
+) -> Result<(), AppError> {
+    let request: MarkShardBackedUpRequest = match serde_json::from_slice(&msg.data) {
+        Ok(r) => r,
+        Err(e) => {
+            return response::send_mark_shard_backed_up_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                KeyError::InvalidRequest(format!("JSON parse error: {}", e)),
+            );
+        }
+    };
+    let action = ShardBackupAction::MarkBackedUp(request.shard);
+    read_shard_store(service, msg, "mark_shard_backed_up", request.user_id, action)
+}
+
+pub fn handle_get_shard_backup_status(
+    service: &mut IdentityService,
+    msg: &Message,
+) -> Result<(), AppError> {
+    let request: GetShardBackupStatusRequest = match serde_json::from_slice(&msg.data) {
+        Ok(r) => r,
+        Err(e) => {
+            return response::send_shard_backup_status_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                KeyError::InvalidRequest(format!("JSON parse error: {}", e)),
+            );
+        }
+    };
+    let action = ShardBackupAction::GetStatus;
+    read_shard_store(service, msg, "get_shard_backup_status", request.user_id, action)
+}
+
+/// Authorize the caller, then read the user's encrypted shard store.
+fn read_shard_store(
+    service: &mut IdentityService,
+    msg: &Message,
+    op_name: &str,
+    user_id: u128,
+    action: ShardBackupAction,
+) -> Result<(), AppError> {
+    let ctx = RequestContext::new(msg.from_pid, msg.cap_slots.clone());
+    if check_user_authorization(msg.from_pid, user_id) == AuthResult::Denied {
+        log_denial(op_name, msg.from_pid, user_id);
+        return send_action_error(&ctx, &action, KeyError::Unauthorized);
+    }
+
+    // Invariant 32: /keys/ paths use Keystore IPC, not VFS
+    service.start_keystore_read(
+        &EncryptedShardStore::storage_path(user_id),
+        PendingKeystoreOp::ReadShardStoreForBackup {
+            ctx,
+            user_id,
+            action,
+        },
+    )
+}
+
+pub fn continue_shard_backup_after_store_read(
+    service: &mut IdentityService,
+    ctx: RequestContext,
+    user_id: u128,
+    action: ShardBackupAction,
+    result: Result<Vec<u8>, String>,
+) -> Result<(), AppError> {
+    let store: EncryptedShardStore = match result
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+    {
+        Some(store) => store,
+        None => return send_action_error(&ctx, &action, KeyError::EncryptedShardsNotFound),
+    };
+
+    match action {
+        ShardBackupAction::Export(shard) => {
+            let result = check_shard(&shard, &store, &[])
+                .map(|()| encode_shard_blob(user_id, &shard))
+                .map_err(KeyError::from);
+            response::send_export_shard_response(ctx.client_pid, &ctx.cap_slots, result)
+        }
+        ShardBackupAction::Verify(inputs) => {
+            let shards: Vec<_> = inputs
+                .into_iter()
+                .map(|input| input.into_shard(user_id))
+                .collect();
+            let report = check_shard_set(&shards, &store);
+            syscall::debug(&format!(
+                "IdentityService: Verified shard set for {:032x}: valid {:?}, {} rejected",
+                user_id,
+                report.valid_indices,
+                report.rejected.len()
+            ));
+            response::send_verify_shards_response(ctx.client_pid, &ctx.cap_slots, Ok(report))
+        }
+        ShardBackupAction::MarkBackedUp(input) => {
+            let index = match input
+                .into_shard(user_id)
+                .and_then(|shard| check_shard(&shard, &store, &[]).map(|()| shard.index))
+            {
+                Ok(index) => index,
+                Err(problem) => {
+                    return response::send_mark_shard_backed_up_error(
+                        ctx.client_pid,
+                        &ctx.cap_slots,
+                        problem.into(),
+                    );
+                }
+            };
+            read_backup_state(service, ctx, user_id, store, Some(index))
+        }
+        ShardBackupAction::GetStatus => read_backup_state(service, ctx, user_id, store, None),
+    }
+}
+
+fn read_backup_state(
+    service: &mut IdentityService,
+    ctx: RequestContext,
+    user_id: u128,
+    store: EncryptedShardStore,
+    mark_index: Option<u8>,
+) -> Result<(), AppError> {
+    service.start_keystore_read(
+        &ShardBackupState::storage_path(user_id),
+        PendingKeystoreOp::ReadShardBackupState {
+            ctx,
+            user_id,
+            store: Box::new(store),
+            mark_index,
+        },
+    )
+}
+
+pub fn continue_shard_backup_after_state_read(
+    service: &mut IdentityService,
+    ctx: RequestContext,
+    user_id: u128,
+    store: EncryptedShardStore,
+    mark_index: Option<u8>,
+    result: Result<Vec<u8>, String>,
+) -> Result<(), AppError> {
+    // Nothing has been backed up until the first shard is confirmed
+    let mut state: ShardBackupState = result
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default();
+
+    let Some(index) = mark_index else {
+        let status = state.status(user_id, &store);
+        return response::send_shard_backup_status_response(
+            ctx.client_pid,
+            &ctx.cap_slots,
+            Ok(status),
+        );
+    };
+
+    state.mark_backed_up(index, syscall::get_wallclock());
+    let status = state.status(user_id, &store);
+    let json = match serde_json::to_vec(&state) {
+        Ok(json) => json,
+        Err(e) => {
+            return response::send_mark_shard_backed_up_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                KeyError::StorageError(format!("Serialization failed: {}", e)),
+            );
+        }
+    };
+    service.start_keystore_write(
+        &ShardBackupState::storage_path(user_id),
+        &json,
+        PendingKeystoreOp::WriteShardBackupState {
+            ctx,
+            status: Box::new(status),
+        },
+    )
+}
+
+pub fn continue_shard_backup_after_state_write(
+    ctx: RequestContext,
+    status: ShardBackupStatus,
+    result: Result<(), String>,
+) -> Result<(), AppError> {
+    let result = result.map(|()| status).map_err(|e| {
+        syscall::debug(&format!(
+            "IdentityService: WriteShardBackupState failed - op=mark_shard_backed_up, error={}",
+            e
+        ));
+        KeyError::StorageError(format!("Keystore write failed for shard backup state: {}", e))
+    });
+    response::send_mark_shard_backed_up_response(ctx.client_pid, &ctx.cap_slots, result)
+}
+
+/// Send `error` on the response channel matching the request's action.
+fn send_action_error(
+    ctx: &RequestContext,
+    action: &ShardBackupAction,
+    error: KeyError,
+) -> Result<(), AppError> {
+    let (pid, slots) = (ctx.client_pid, &ctx.cap_slots);
+    match action {
+        ShardBackupAction::Export(_) => response::send_export_shard_error(pid, slots, error),
+        ShardBackupAction::Verify(_) => response::send_verify_shards_error(pid, slots, error),
+        ShardBackupAction::MarkBackedUp(_) => {
+            response::send_mark_shard_backed_up_error(pid, slots, error)
+        }
+        ShardBackupAction::GetStatus => response::send_shard_backup_status_error(pid, slots, error),
+    }
+}
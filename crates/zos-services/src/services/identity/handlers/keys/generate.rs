@@ -10,6 +10,7 @@ use crate::services::identity::utils::bytes_to_hex;
 use crate::services::identity::pending::{PendingKeystoreOp, PendingStorageOp, RequestContext};
 use crate::services::identity::response;
 use crate::services::identity::{check_user_authorization, log_denial, AuthResult, IdentityService};
+use zos_identity::backup::{shard_commitment, ShardCommitment};
 use zos_identity::crypto::{
     create_kdf_params, derive_identity_signing_keypair, derive_key_from_password_public,
     encrypt_shard_with_key, select_shards_to_encrypt, split_neural_key, validate_password,
@@ -417,6 +418,13 @@ fn build_generation_result(
         user_id,
         encrypted_shards,
         external_shard_indices: external_indices.to_vec(),
+        external_shard_commitments: external_shards
+            .iter()
+            .map(|shard| ShardCommitment {
+                index: shard.index,
+                commitment: shard_commitment(user_id, shard),
+            })
+            .collect(),
         kdf,
         created_at,
     };
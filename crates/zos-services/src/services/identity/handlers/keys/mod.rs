@@ -2,6 +2,7 @@
 //!
 //! Handlers for:
 //! - Neural key generation and recovery
+//! - Backup shard export, threshold verification and backup tracking
 //! - Machine key CRUD operations (create, list, get, revoke, rotate)
 //!
 //! # Dual User ID Pattern (CRITICAL)
@@ -80,6 +81,7 @@
 //! - Silent fallthrough on parse errors (must return InvalidRequest)
 //! - Processing requests without authorization check
 
+mod backup;
 mod generate;
 mod recover;
 mod machine;
@@ -94,10 +96,21 @@ pub use generate::{
     continue_generate_after_exists_check,
 };
 
+pub use backup::{
+    handle_export_shard,
+    handle_verify_shards,
+    handle_mark_shard_backed_up,
+    handle_get_shard_backup_status,
+    continue_shard_backup_after_store_read,
+    continue_shard_backup_after_state_read,
+    continue_shard_backup_after_state_write,
+};
+
 pub use recover::{
     handle_recover_neural_key,
     continue_recover_after_identity_read,
     handle_get_identity_key,
+    handle_get_key_fingerprint,
 };
 
 pub use machine::{
@@ -16,7 +16,7 @@ use zos_identity::crypto::{
 use zos_apps::syscall;
 use zos_apps::{AppError, Message};
 use zos_identity::ipc::{
-    GetIdentityKeyRequest, NeuralKeyGenerated, NeuralShard, PublicIdentifiers,
+    GetIdentityKeyRequest, GetKeyFingerprintRequest, NeuralKeyGenerated, NeuralShard, PublicIdentifiers,
     RecoverNeuralKeyRequest,
 };
 use zos_identity::keystore::LocalKeyStore;
@@ -275,3 +275,37 @@ pub fn handle_get_identity_key(
         PendingKeystoreOp::GetIdentityKey { ctx },
     )
 }
+
+/// Handle MSG_GET_KEY_FINGERPRINT.
+///
+/// Only public keys leave the service, so there is no authorization check:
+/// any process may fingerprint a user's identity key, which is what lets
+/// contacts verify it.
+pub fn handle_get_key_fingerprint(
+    service: &mut IdentityService,
+    msg: &Message,
+) -> Result<(), AppError> {
+    // Rule 1: Parse request - return InvalidRequest on parse failure
+    let request: GetKeyFingerprintRequest = match serde_json::from_slice(&msg.data) {
+        Ok(r) => r,
+        Err(e) => {
+            syscall::debug(&format!("IdentityService: Failed to parse request: {}", e));
+            return response::send_get_key_fingerprint_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                KeyError::InvalidRequest(format!("JSON parse error: {}", e)),
+            );
+        }
+    };
+
+    let key_path = LocalKeyStore::storage_path(request.user_id);
+    let ctx = RequestContext::new(msg.from_pid, msg.cap_slots.clone());
+    // Invariant 32: /keys/ paths use Keystore IPC, not VFS
+    service.start_keystore_read(
+        &key_path,
+        PendingKeystoreOp::GetKeyFingerprint {
+            ctx,
+            user_id: request.user_id,
+        },
+    )
+}
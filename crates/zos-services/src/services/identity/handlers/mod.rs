@@ -3,6 +3,7 @@
 //! Organized by functional domain:
 //! - `keys`: Neural key and machine key operations
 //! - `session`: ZID login/enrollment flows
+//! - `enrollment`: Approval handshake for adding machines to an identity
 //! - `credentials`: Credential management
 //! - `preferences`: Identity preferences (default key scheme, etc.)
 //!
@@ -26,6 +27,7 @@
 //! - No intermediate responses
 
 pub mod credentials;
+pub mod enrollment;
 pub mod keys;
 pub mod preferences;
 pub mod session;
@@ -8,6 +8,8 @@
 //! ## Success Conditions
 //! - Get preferences: Preferences read (or default), response sent
 //! - Set key scheme: Preferences read, updated, written, response sent
+//! - Set login items: List validated, preferences read, updated, written,
+//!   response sent
 //!
 //! ## Acceptable Partial Failure
 //! - Read failure returns default preferences (not an error)
@@ -20,8 +22,8 @@
 use alloc::format;
 use zos_apps::{syscall, AppError, Message};
 use zos_identity::ipc::{
-    GetIdentityPreferencesRequest, IdentityPreferences,
-    SetDefaultKeySchemeRequest, SetDefaultMachineKeyRequest,
+    validate_login_items, GetIdentityPreferencesRequest, IdentityPreferences,
+    SetDefaultKeySchemeRequest, SetDefaultMachineKeyRequest, SetLoginItemsRequest,
 };
 use zos_identity::KeyError;
 
@@ -150,3 +152,49 @@ pub fn handle_set_default_machine_key(
         },
     )
 }
+
+/// Handle set login items - replace the login item list in VFS preferences
+pub fn handle_set_login_items(
+    service: &mut IdentityService,
+    msg: &Message,
+) -> Result<(), AppError> {
+    // Rule 1: Parse request - return InvalidRequest on parse failure
+    let request: SetLoginItemsRequest = match serde_json::from_slice(&msg.data) {
+        Ok(r) => r,
+        Err(e) => {
+            syscall::debug(&format!("IdentityService: Failed to parse request: {}", e));
+            return response::send_set_login_items_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                KeyError::InvalidRequest(format!("JSON parse error: {}", e)),
+            );
+        }
+    };
+
+    // Rule 4: Authorization check (FAIL-CLOSED)
+    if check_user_authorization(msg.from_pid, request.user_id) == AuthResult::Denied {
+        log_denial("set_login_items", msg.from_pid, request.user_id);
+        return response::send_set_login_items_error(
+            msg.from_pid,
+            &msg.cap_slots,
+            KeyError::Unauthorized,
+        );
+    }
+
+    if let Err(e) = validate_login_items(&request.items) {
+        return response::send_set_login_items_error(msg.from_pid, &msg.cap_slots, e);
+    }
+
+    let prefs_path = IdentityPreferences::storage_path(request.user_id);
+
+    // Read existing preferences first (or use default), then replace login_items
+    let ctx = RequestContext::new(msg.from_pid, msg.cap_slots.clone());
+    service.start_vfs_read(
+        &prefs_path,
+        PendingStorageOp::ReadPreferencesForLoginItems {
+            ctx,
+            user_id: request.user_id,
+            items: request.items,
+        },
+    )
+}
@@ -5,6 +5,7 @@ extern crate alloc;
 use alloc::format;
 use alloc::string::String;
 
+use crate::services::identity::handlers::enrollment;
 use crate::services::identity::pending::PendingKeystoreOp;
 use crate::services::identity::{response, IdentityService};
 use zos_apps::syscall;
@@ -44,11 +45,15 @@ pub fn dispatch_keystore_delete_result(
             }
             Ok(())
         }
+        PendingKeystoreOp::DeleteEnrollment { ctx, outcome } => {
+            enrollment::finish_enrollment_after_delete(ctx, outcome, result)
+        }
         // Operations that should NOT receive a delete response
         PendingKeystoreOp::CheckKeyExists { ctx, .. }
         | PendingKeystoreOp::WriteKeyStore { ctx, .. }
         | PendingKeystoreOp::WriteEncryptedShards { ctx, .. }
         | PendingKeystoreOp::GetIdentityKey { ctx }
+        | PendingKeystoreOp::GetKeyFingerprint { ctx, .. }
         | PendingKeystoreOp::ReadIdentityForRecovery { ctx, .. }
         | PendingKeystoreOp::WriteRecoveredKeyStore { ctx, .. }
         | PendingKeystoreOp::ReadIdentityForMachine { ctx, .. }
@@ -65,7 +70,14 @@ pub fn dispatch_keystore_delete_result(
         | PendingKeystoreOp::ReadMachineKeyForZidEnroll { ctx, .. }
         | PendingKeystoreOp::ReadIdentityForMachineEnroll { ctx, .. }
         | PendingKeystoreOp::ReadEncryptedShardsForMachineEnroll { ctx, .. }
-        | PendingKeystoreOp::WriteMachineKeyForEnroll { ctx, .. } => {
+        | PendingKeystoreOp::WriteMachineKeyForEnroll { ctx, .. }
+        | PendingKeystoreOp::WriteEnrollmentRequest { ctx, .. }
+        | PendingKeystoreOp::ReadMachineKeyForEnrollmentDecision { ctx, .. }
+        | PendingKeystoreOp::ReadEnrollmentForCompletion { ctx, .. }
+        | PendingKeystoreOp::ReadShardStoreForBackup { ctx, .. }
+        | PendingKeystoreOp::ReadShardBackupState { ctx, .. }
+        | PendingKeystoreOp::WriteShardBackupState { ctx, .. }
+        | PendingKeystoreOp::WriteEnrolledMachineKey { ctx, .. } => {
             syscall::debug(&format!(
                 "IdentityService: STATE_MACHINE_ERROR - unexpected keystore delete result for non-delete op, client_pid={}",
                 ctx.client_pid
@@ -44,6 +44,7 @@ pub fn dispatch_keystore_exists_result(
         PendingKeystoreOp::WriteKeyStore { ctx, .. }
         | PendingKeystoreOp::WriteEncryptedShards { ctx, .. }
         | PendingKeystoreOp::GetIdentityKey { ctx }
+        | PendingKeystoreOp::GetKeyFingerprint { ctx, .. }
         | PendingKeystoreOp::ReadIdentityForRecovery { ctx, .. }
         | PendingKeystoreOp::WriteRecoveredKeyStore { ctx, .. }
         | PendingKeystoreOp::ReadIdentityForMachine { ctx, .. }
@@ -62,7 +63,15 @@ pub fn dispatch_keystore_exists_result(
         | PendingKeystoreOp::ReadMachineKeyForZidEnroll { ctx, .. }
         | PendingKeystoreOp::ReadIdentityForMachineEnroll { ctx, .. }
         | PendingKeystoreOp::ReadEncryptedShardsForMachineEnroll { ctx, .. }
-        | PendingKeystoreOp::WriteMachineKeyForEnroll { ctx, .. } => {
+        | PendingKeystoreOp::WriteMachineKeyForEnroll { ctx, .. }
+        | PendingKeystoreOp::WriteEnrollmentRequest { ctx, .. }
+        | PendingKeystoreOp::ReadMachineKeyForEnrollmentDecision { ctx, .. }
+        | PendingKeystoreOp::ReadEnrollmentForCompletion { ctx, .. }
+        | PendingKeystoreOp::ReadShardStoreForBackup { ctx, .. }
+        | PendingKeystoreOp::ReadShardBackupState { ctx, .. }
+        | PendingKeystoreOp::WriteShardBackupState { ctx, .. }
+        | PendingKeystoreOp::WriteEnrolledMachineKey { ctx, .. }
+        | PendingKeystoreOp::DeleteEnrollment { ctx, .. } => {
             syscall::debug(&format!(
                 "IdentityService: STATE_MACHINE_ERROR - unexpected keystore exists result for non-exists op, client_pid={}",
                 ctx.client_pid
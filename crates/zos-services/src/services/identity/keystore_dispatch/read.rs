@@ -6,11 +6,15 @@ use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
 
-use crate::services::identity::handlers::{keys, session};
+use crate::services::identity::handlers::{enrollment, keys, session};
 use crate::services::identity::pending::{PendingKeystoreOp, RequestContext};
+use crate::services::identity::utils::bytes_to_hex;
 use crate::services::identity::{response, IdentityService};
 use zos_apps::syscall;
 use zos_apps::AppError;
+use zos_identity::error::ZidError;
+use zos_identity::fingerprint::key_fingerprint;
+use zos_identity::ipc::KeyFingerprint;
 use zos_identity::keystore::{EncryptedShardStore, LocalKeyStore, MachineKeyRecord};
 use zos_identity::KeyError;
 
@@ -24,9 +28,20 @@ pub fn dispatch_keystore_read_result(
         PendingKeystoreOp::GetIdentityKey { ctx } => {
             handle_get_identity_key_read(ctx, result)
         }
+        PendingKeystoreOp::GetKeyFingerprint { ctx, user_id } => {
+            handle_get_key_fingerprint_read(ctx, user_id, result)
+        }
         PendingKeystoreOp::ReadIdentityForRecovery { ctx, user_id, zid_shards } => {
             handle_read_identity_for_recovery(service, ctx, user_id, zid_shards, result)
         }
+        PendingKeystoreOp::ReadShardStoreForBackup { ctx, user_id, action } => {
+            keys::continue_shard_backup_after_store_read(service, ctx, user_id, action, result)
+        }
+        PendingKeystoreOp::ReadShardBackupState { ctx, user_id, store, mark_index } => {
+            keys::continue_shard_backup_after_state_read(
+                service, ctx, user_id, *store, mark_index, result,
+            )
+        }
         PendingKeystoreOp::ReadIdentityForMachine { ctx, request } => {
             handle_read_identity_for_machine(service, ctx, request, result)
         }
@@ -54,6 +69,26 @@ pub fn dispatch_keystore_read_result(
         PendingKeystoreOp::ReadMachineKeyForZidEnroll { ctx, user_id, zid_endpoint } => {
             handle_read_machine_key_for_zid_enroll(service, ctx, user_id, zid_endpoint, result)
         }
+        PendingKeystoreOp::ReadMachineKeyForEnrollmentDecision { ctx, zid_endpoint, enrollment, outcome } => {
+            match result {
+                Ok(data) => enrollment::continue_decide_enrollment_after_read(
+                    service, ctx, zid_endpoint, *enrollment, outcome, &data,
+                ),
+                Err(_) => response::send_decide_enrollment_error(
+                    ctx.client_pid,
+                    &ctx.cap_slots,
+                    ZidError::MachineKeyNotFound,
+                ),
+            }
+        }
+        PendingKeystoreOp::ReadEnrollmentForCompletion { ctx, user_id } => match result {
+            Ok(data) => enrollment::continue_complete_enrollment_after_read(service, ctx, user_id, &data),
+            Err(_) => response::send_complete_enrollment_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                ZidError::EnrollmentNotFound,
+            ),
+        },
         // Operations that should NOT receive a read response
         PendingKeystoreOp::CheckKeyExists { ctx, .. }
         | PendingKeystoreOp::WriteKeyStore { ctx, .. }
@@ -66,7 +101,11 @@ pub fn dispatch_keystore_read_result(
         | PendingKeystoreOp::DeleteMachineKey { ctx, .. }
         | PendingKeystoreOp::DeleteIdentityKeyAfterShardFailure { ctx, .. }
         | PendingKeystoreOp::WriteRotatedMachineKey { ctx, .. }
-        | PendingKeystoreOp::WriteMachineKeyForEnroll { ctx, .. } => {
+        | PendingKeystoreOp::WriteMachineKeyForEnroll { ctx, .. }
+        | PendingKeystoreOp::WriteEnrollmentRequest { ctx, .. }
+        | PendingKeystoreOp::WriteEnrolledMachineKey { ctx, .. }
+        | PendingKeystoreOp::WriteShardBackupState { ctx, .. }
+        | PendingKeystoreOp::DeleteEnrollment { ctx, .. } => {
             syscall::debug(&format!(
                 "IdentityService: STATE_MACHINE_ERROR - unexpected keystore read result for non-read op, client_pid={}",
                 ctx.client_pid
@@ -108,6 +147,43 @@ fn handle_get_identity_key_read(
     }
 }
 
+fn handle_get_key_fingerprint_read(
+    ctx: RequestContext,
+    user_id: u128,
+    result: Result<Vec<u8>, String>,
+) -> Result<(), AppError> {
+    let key_store = match result {
+        Ok(data) => match serde_json::from_slice::<LocalKeyStore>(&data) {
+            Ok(key_store) => key_store,
+            Err(e) => {
+                syscall::debug(&format!(
+                    "IdentityService: Failed to parse stored keys from keystore: {}",
+                    e
+                ));
+                return response::send_get_key_fingerprint_error(
+                    ctx.client_pid,
+                    &ctx.cap_slots,
+                    KeyError::StorageError(format!("Parse failed: {}", e)),
+                );
+            }
+        },
+        Err(_) => {
+            return response::send_get_key_fingerprint_error(
+                ctx.client_pid,
+                &ctx.cap_slots,
+                KeyError::KeysNotFound,
+            );
+        }
+    };
+    let fingerprint = KeyFingerprint {
+        user_id,
+        identity_signing_pub_key: bytes_to_hex(&key_store.identity_signing_public_key),
+        machine_encryption_pub_key: bytes_to_hex(&key_store.machine_encryption_public_key),
+        fingerprint: key_fingerprint(&key_store.identity_signing_public_key),
+    };
+    response::send_get_key_fingerprint_response(ctx.client_pid, &ctx.cap_slots, Ok(fingerprint))
+}
+
 fn handle_read_identity_for_recovery(
     service: &mut IdentityService,
     ctx: RequestContext,
@@ -5,7 +5,7 @@ extern crate alloc;
 use alloc::format;
 use alloc::string::String;
 
-use crate::services::identity::handlers::session;
+use crate::services::identity::handlers::{enrollment, keys, session};
 use crate::services::identity::pending::{PendingKeystoreOp, PendingStorageOp};
 use crate::services::identity::{response, IdentityService};
 use zos_apps::syscall;
@@ -29,6 +29,9 @@ pub fn dispatch_keystore_write_result(
         PendingKeystoreOp::WriteRecoveredKeyStore { ctx, result: key_result, .. } => {
             handle_write_recovered_key_store(ctx, key_result, result)
         }
+        PendingKeystoreOp::WriteShardBackupState { ctx, status } => {
+            keys::continue_shard_backup_after_state_write(ctx, *status, result)
+        }
         PendingKeystoreOp::WriteMachineKey { ctx, record, .. } => {
             handle_write_machine_key(ctx, record, result)
         }
@@ -46,9 +49,18 @@ pub fn dispatch_keystore_write_result(
                 machine_signing_sk, machine_encryption_sk, result
             )
         }
+        PendingKeystoreOp::WriteEnrollmentRequest { ctx, local } => {
+            enrollment::continue_request_enrollment_after_write(service, ctx, *local, result)
+        }
+        PendingKeystoreOp::WriteEnrolledMachineKey { ctx, user_id, request_id, record } => {
+            enrollment::continue_complete_enrollment_after_write(
+                service, ctx, user_id, request_id, *record, result,
+            )
+        }
         // Operations that should NOT receive a write response
         PendingKeystoreOp::CheckKeyExists { ctx, .. }
         | PendingKeystoreOp::GetIdentityKey { ctx }
+        | PendingKeystoreOp::GetKeyFingerprint { ctx, .. }
         | PendingKeystoreOp::ReadIdentityForRecovery { ctx, .. }
         | PendingKeystoreOp::ReadIdentityForMachine { ctx, .. }
         | PendingKeystoreOp::ReadEncryptedShardsForMachine { ctx, .. }
@@ -63,7 +75,12 @@ pub fn dispatch_keystore_write_result(
         | PendingKeystoreOp::ReadMachineKeyForZidLogin { ctx, .. }
         | PendingKeystoreOp::ReadMachineKeyForZidEnroll { ctx, .. }
         | PendingKeystoreOp::ReadIdentityForMachineEnroll { ctx, .. }
-        | PendingKeystoreOp::ReadEncryptedShardsForMachineEnroll { ctx, .. } => {
+        | PendingKeystoreOp::ReadEncryptedShardsForMachineEnroll { ctx, .. }
+        | PendingKeystoreOp::ReadMachineKeyForEnrollmentDecision { ctx, .. }
+        | PendingKeystoreOp::ReadEnrollmentForCompletion { ctx, .. }
+        | PendingKeystoreOp::ReadShardStoreForBackup { ctx, .. }
+        | PendingKeystoreOp::ReadShardBackupState { ctx, .. }
+        | PendingKeystoreOp::DeleteEnrollment { ctx, .. } => {
             syscall::debug(&format!(
                 "IdentityService: STATE_MACHINE_ERROR - unexpected keystore write result for non-write op, client_pid={}",
                 ctx.client_pid
@@ -33,10 +33,17 @@
 //! - `MSG_GENERATE_NEURAL_KEY (0x7054)`: Generate a new Neural Key
 //! - `MSG_RECOVER_NEURAL_KEY (0x7056)`: Recover from shards
 //! - `MSG_GET_IDENTITY_KEY (0x7052)`: Get stored public keys
+//! - `MSG_GET_KEY_FINGERPRINT (0x7032)`: Public keys and identity key fingerprint (any caller)
+//! - `MSG_EXPORT_SHARD (0x7058)`: Encode a backup shard as a QR-encodable blob
+//! - `MSG_VERIFY_SHARDS (0x705A)`: Check a shard set against the recovery threshold
+//! - `MSG_MARK_SHARD_BACKED_UP (0x705C)` / `MSG_GET_SHARD_BACKUP_STATUS (0x705E)`:
+//!   Track which backup shards the user has saved
 //! - `MSG_CREATE_MACHINE_KEY (0x7060)`: Create machine record
 //! - `MSG_LIST_MACHINE_KEYS (0x7062)`: List all machines
 //! - `MSG_REVOKE_MACHINE_KEY (0x7066)`: Delete machine record
 //! - `MSG_ROTATE_MACHINE_KEY (0x7068)`: Update machine keys
+//! - `MSG_REQUEST_ENROLLMENT (0x7070)`: Ask to join an identity (new machine)
+//! - `MSG_DECIDE_ENROLLMENT (0x7074)`: Approve or reject a join (enrolled machine)
 //!
 //! # Architecture
 //!
@@ -58,6 +65,8 @@ extern crate alloc;
 
 // Split modules for dispatch logic
 mod auth;
+mod checkpoint;
+mod drain;
 mod keystore_dispatch;
 mod keystore_helpers;
 mod network_dispatch;
@@ -86,20 +95,24 @@ pub const MAX_PENDING_NET_OPS: usize = 32;
 #[cfg(test)]
 mod tests;
 
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 
 use crate::manifests::IDENTITY_MANIFEST;
+use checkpoint::{IdentityState, InterruptedRequest};
 use pending::{PendingKeystoreOp, PendingNetworkOp, PendingStorageOp};
 use zos_apps::syscall;
-use zos_apps::{AppContext, AppError, AppManifest, ControlFlow, Message, ZeroApp};
+use zos_apps::{
+    AppContext, AppError, AppManifest, ControlFlow, Drain, Message, StateStore, StatefulService,
+    ZeroApp,
+};
 use zos_process::{
-    identity_cred, identity_key, identity_machine, identity_prefs, identity_zid, net,
+    identity_cred, identity_enroll, identity_key, identity_machine, identity_prefs, identity_query,
+    identity_zid, net,
 };
 use zos_vfs::async_client;
 use zos_vfs::client::keystore_async;
 
 /// IdentityService - manages user cryptographic identities
-#[derive(Default)]
 pub struct IdentityService {
     /// Whether we have registered with init
     pub registered: bool,
@@ -115,6 +128,31 @@ pub struct IdentityService {
     pub next_keystore_op_id: u32,
     /// Pending network operations: request_id -> operation context
     pub pending_net_ops: BTreeMap<u32, PendingNetworkOp>,
+    /// Client requests still in progress (checkpointed)
+    pub client_requests: BTreeSet<InterruptedRequest>,
+    /// Whether the checkpoint read has been issued
+    pub restore_requested: bool,
+    /// State checkpointed across restarts
+    pub checkpoint: StatefulService<IdentityState>,
+    /// Drain before restart
+    pub drain: Drain,
+}
+
+impl Default for IdentityService {
+    fn default() -> Self {
+        Self {
+            registered: false,
+            pending_vfs_ops: BTreeMap::new(),
+            next_vfs_op_id: 0,
+            pending_keystore_ops: BTreeMap::new(),
+            next_keystore_op_id: 0,
+            pending_net_ops: BTreeMap::new(),
+            client_requests: BTreeSet::new(),
+            restore_requested: false,
+            checkpoint: StatefulService::new("identity", StateStore::Vfs),
+            drain: Drain::default(),
+        }
+    }
 }
 
 impl ZeroApp for IdentityService {
@@ -148,6 +186,7 @@ impl ZeroApp for IdentityService {
                 Ok(_) => {
                     self.registered = true;
                     syscall::debug("IdentityService: Registration message sent successfully");
+                    let _ = syscall::send(0, zos_process::init::MSG_SERVICE_READY, &[]);
                 }
                 Err(e) => {
                     syscall::debug(&alloc::format!(
@@ -157,6 +196,12 @@ impl ZeroApp for IdentityService {
                 }
             }
         }
+        if self.registered && !self.restore_requested {
+            self.start_state_restore();
+        }
+        let now_ms = ctx.uptime_ns / 1_000_000;
+        self.pump_checkpoint(now_ms);
+        self.pump_drain(now_ms);
         ControlFlow::Yield
     }
 
@@ -176,7 +221,14 @@ impl ZeroApp for IdentityService {
             return self.handle_keystore_result(&msg);
         }
 
-        match msg.tag {
+        if msg.tag == zos_process::init::MSG_SERVICE_DRAIN {
+            return self.handle_drain(&msg);
+        }
+        if self.refuses_while_draining(msg.tag) {
+            return self.refuse_while_draining(&msg);
+        }
+
+        let result = match msg.tag {
             identity_key::MSG_GENERATE_NEURAL_KEY => {
                 handlers::keys::handle_generate_neural_key(self, &msg)
             }
@@ -186,6 +238,21 @@ impl ZeroApp for IdentityService {
             identity_key::MSG_GET_IDENTITY_KEY => {
                 handlers::keys::handle_get_identity_key(self, &msg)
             }
+            identity_query::MSG_GET_KEY_FINGERPRINT => {
+                handlers::keys::handle_get_key_fingerprint(self, &msg)
+            }
+            identity_key::MSG_EXPORT_SHARD => {
+                handlers::keys::handle_export_shard(self, &msg)
+            }
+            identity_key::MSG_VERIFY_SHARDS => {
+                handlers::keys::handle_verify_shards(self, &msg)
+            }
+            identity_key::MSG_MARK_SHARD_BACKED_UP => {
+                handlers::keys::handle_mark_shard_backed_up(self, &msg)
+            }
+            identity_key::MSG_GET_SHARD_BACKUP_STATUS => {
+                handlers::keys::handle_get_shard_backup_status(self, &msg)
+            }
             identity_machine::MSG_CREATE_MACHINE_KEY => {
                 handlers::keys::handle_create_machine_key(self, &msg)
             }
@@ -204,6 +271,18 @@ impl ZeroApp for IdentityService {
             identity_machine::MSG_CREATE_MACHINE_KEY_AND_ENROLL => {
                 handlers::keys::handle_create_machine_key_and_enroll(self, &msg)
             }
+            identity_enroll::MSG_REQUEST_ENROLLMENT => {
+                handlers::enrollment::handle_request_enrollment(self, &msg)
+            }
+            identity_enroll::MSG_LIST_PENDING_ENROLLMENTS => {
+                handlers::enrollment::handle_list_pending_enrollments(self, &msg)
+            }
+            identity_enroll::MSG_DECIDE_ENROLLMENT => {
+                handlers::enrollment::handle_decide_enrollment(self, &msg)
+            }
+            identity_enroll::MSG_COMPLETE_ENROLLMENT => {
+                handlers::enrollment::handle_complete_enrollment(self, &msg)
+            }
             identity_cred::MSG_ATTACH_EMAIL => {
                 handlers::credentials::handle_attach_email(self, &msg)
             }
@@ -231,15 +310,20 @@ impl ZeroApp for IdentityService {
             identity_prefs::MSG_SET_DEFAULT_MACHINE_KEY => {
                 handlers::preferences::handle_set_default_machine_key(self, &msg)
             }
-            net::MSG_NET_RESULT => self.handle_net_result(&msg),
+            identity_prefs::MSG_SET_LOGIN_ITEMS => {
+                handlers::preferences::handle_set_login_items(self, &msg)
+            }
+            net::MSG_NET_RESULT => return self.handle_net_result(&msg),
             _ => {
                 syscall::debug(&alloc::format!(
                     "IdentityService: Unknown message tag 0x{:x}",
                     msg.tag
                 ));
-                Ok(())
+                return Ok(());
             }
-        }
+        };
+        self.track_client_request(&msg);
+        result
     }
 
     fn shutdown(&mut self, _ctx: &AppContext) {
@@ -3,7 +3,7 @@
 //! Handles MSG_NET_RESULT messages and routes to appropriate handlers
 //! based on the pending network operation type.
 
-use super::handlers::{credentials, session};
+use super::handlers::{credentials, enrollment, session};
 use super::network::{self as network_handlers, NetworkHandlerResult};
 use super::pending::PendingNetworkOp;
 use super::IdentityService;
@@ -486,6 +486,24 @@ impl IdentityService {
                     _ => Ok(()),
                 }
             }
+            PendingNetworkOp::SubmitEnrollmentRequest { ctx, enrollment } => {
+                enrollment::continue_request_enrollment_after_submit(ctx, *enrollment, http_response)
+            }
+            PendingNetworkOp::ListPendingEnrollments { ctx } => {
+                enrollment::continue_list_pending_enrollments(ctx, http_response)
+            }
+            PendingNetworkOp::SubmitEnrollmentDecision { ctx } => {
+                enrollment::continue_decide_enrollment_after_submit(ctx, http_response)
+            }
+            PendingNetworkOp::FetchEnrollmentStatus { ctx, user_id, local } => {
+                enrollment::continue_complete_enrollment_after_status(
+                    self,
+                    ctx,
+                    user_id,
+                    *local,
+                    http_response,
+                )
+            }
         }
     }
 }
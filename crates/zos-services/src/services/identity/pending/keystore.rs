@@ -4,8 +4,13 @@ extern crate alloc;
 
 use alloc::string::String;
 use alloc::vec::Vec;
-use zos_identity::ipc::{CreateMachineKeyAndEnrollRequest, CreateMachineKeyRequest, NeuralKeyGenerated};
-use zos_identity::keystore::MachineKeyRecord;
+use zos_identity::backup::{ShardBackupStatus, ShardInput};
+use zos_identity::error::ZidError;
+use zos_identity::ipc::{
+    CreateMachineKeyAndEnrollRequest, CreateMachineKeyRequest, EnrollmentOutcome,
+    LocalEnrollment, NeuralKeyGenerated, NeuralShard, PendingEnrollment,
+};
+use zos_identity::keystore::{EncryptedShardStore, MachineKeyRecord};
 
 use super::RequestContext;
 
@@ -49,6 +54,11 @@ pub enum PendingKeystoreOp {
     GetIdentityKey {
         ctx: RequestContext,
     },
+    /// Read identity key to fingerprint it
+    GetKeyFingerprint {
+        ctx: RequestContext,
+        user_id: u128,
+    },
     /// Read existing identity for recovery verification (SECURITY)
     ReadIdentityForRecovery {
         ctx: RequestContext,
@@ -63,6 +73,29 @@ pub enum PendingKeystoreOp {
         json_bytes: Vec<u8>,
     },
 
+    // =========================================================================
+    // Shard backup operations (stored in keystore)
+    // =========================================================================
+    /// Read the encrypted shard store (commitments and external indices)
+    ReadShardStoreForBackup {
+        ctx: RequestContext,
+        user_id: u128,
+        action: ShardBackupAction,
+    },
+    /// Read which shards are already backed up
+    ReadShardBackupState {
+        ctx: RequestContext,
+        user_id: u128,
+        store: Box<EncryptedShardStore>,
+        /// Shard index to record as backed up, if any
+        mark_index: Option<u8>,
+    },
+    /// Write the updated backup state, then respond with the new status
+    WriteShardBackupState {
+        ctx: RequestContext,
+        status: Box<ShardBackupStatus>,
+    },
+
     // =========================================================================
     // Machine Key operations (stored in keystore)
     // =========================================================================
@@ -186,4 +219,91 @@ pub enum PendingKeystoreOp {
         /// Machine encryption SK (for storage)
         machine_encryption_sk: [u8; 32],
     },
+
+    // =========================================================================
+    // Machine enrollment approval operations (stored in keystore)
+    // =========================================================================
+    /// Write a new enrollment request (with its seeds) before submitting it
+    WriteEnrollmentRequest {
+        ctx: RequestContext,
+        local: Box<LocalEnrollment>,
+    },
+    /// Read the approving machine's key to sign a decision
+    ReadMachineKeyForEnrollmentDecision {
+        ctx: RequestContext,
+        zid_endpoint: String,
+        enrollment: Box<PendingEnrollment>,
+        outcome: EnrollmentOutcome,
+    },
+    /// Read a pending enrollment request to collect its decision
+    ReadEnrollmentForCompletion {
+        ctx: RequestContext,
+        user_id: u128,
+    },
+    /// Write the machine key of an approved enrollment
+    WriteEnrolledMachineKey {
+        ctx: RequestContext,
+        user_id: u128,
+        request_id: u128,
+        record: Box<MachineKeyRecord>,
+    },
+    /// Delete a decided or expired enrollment request, then respond
+    DeleteEnrollment {
+        ctx: RequestContext,
+        /// Response to send once the request is deleted
+        outcome: Result<Box<MachineKeyRecord>, ZidError>,
+    },
+}
+
+/// Shard backup request waiting on the encrypted shard store.
+#[derive(Clone)]
+pub enum ShardBackupAction {
+    /// Export a shard as a blob
+    Export(NeuralShard),
+    /// Check a shard set against the threshold
+    Verify(Vec<ShardInput>),
+    /// Record a shard as backed up
+    MarkBackedUp(ShardInput),
+    /// Report backup status
+    GetStatus,
+}
+
+impl PendingKeystoreOp {
+    /// PID of the client awaiting the response.
+    pub fn client_pid(&self) -> u32 {
+        match self {
+            PendingKeystoreOp::CheckKeyExists { ctx, .. } |
+            PendingKeystoreOp::WriteKeyStore { ctx, .. } |
+            PendingKeystoreOp::WriteEncryptedShards { ctx, .. } |
+            PendingKeystoreOp::DeleteIdentityKeyAfterShardFailure { ctx, .. } |
+            PendingKeystoreOp::GetIdentityKey { ctx, .. } |
+            PendingKeystoreOp::GetKeyFingerprint { ctx, .. } |
+            PendingKeystoreOp::ReadIdentityForRecovery { ctx, .. } |
+            PendingKeystoreOp::WriteRecoveredKeyStore { ctx, .. } |
+            PendingKeystoreOp::ReadShardStoreForBackup { ctx, .. } |
+            PendingKeystoreOp::ReadShardBackupState { ctx, .. } |
+            PendingKeystoreOp::WriteShardBackupState { ctx, .. } |
+            PendingKeystoreOp::ReadIdentityForMachine { ctx, .. } |
+            PendingKeystoreOp::ReadEncryptedShardsForMachine { ctx, .. } |
+            PendingKeystoreOp::WriteMachineKey { ctx, .. } |
+            PendingKeystoreOp::ListMachineKeys { ctx, .. } |
+            PendingKeystoreOp::ReadMachineKey { ctx, .. } |
+            PendingKeystoreOp::DeleteMachineKey { ctx, .. } |
+            PendingKeystoreOp::ReadMachineForRotate { ctx, .. } |
+            PendingKeystoreOp::WriteRotatedMachineKey { ctx, .. } |
+            PendingKeystoreOp::ReadSingleMachineKey { ctx, .. } |
+            PendingKeystoreOp::ListMachineKeysForZidLogin { ctx, .. } |
+            PendingKeystoreOp::ReadMachineKeyForZidLogin { ctx, .. } |
+            PendingKeystoreOp::ListMachineKeysForZidEnroll { ctx, .. } |
+            PendingKeystoreOp::ReadMachineKeyForZidEnroll { ctx, .. } |
+            PendingKeystoreOp::ReadIdentityForMachineEnroll { ctx, .. } |
+            PendingKeystoreOp::ReadEncryptedShardsForMachineEnroll { ctx, .. } |
+            PendingKeystoreOp::WriteMachineKeyForEnroll { ctx, .. } |
+            PendingKeystoreOp::WriteEnrollmentRequest { ctx, .. } |
+            PendingKeystoreOp::ReadMachineKeyForEnrollmentDecision { ctx, .. } |
+            PendingKeystoreOp::ReadEnrollmentForCompletion { ctx, .. } |
+            PendingKeystoreOp::WriteEnrolledMachineKey { ctx, .. } |
+            PendingKeystoreOp::DeleteEnrollment { ctx, .. } => ctx.client_pid,
+        }
+    }
 }
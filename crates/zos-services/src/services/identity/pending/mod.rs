@@ -13,7 +13,7 @@ mod keystore;
 mod network;
 
 pub use storage::{ExpectedVfsResponse, PendingStorageOp};
-pub use keystore::PendingKeystoreOp;
+pub use keystore::{PendingKeystoreOp, ShardBackupAction};
 pub use network::PendingNetworkOp;
 
 extern crate alloc;
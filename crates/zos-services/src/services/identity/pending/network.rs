@@ -3,6 +3,7 @@
 extern crate alloc;
 
 use alloc::string::String;
+use zos_identity::ipc::{LocalEnrollment, PendingEnrollment};
 use zos_identity::keystore::MachineKeyRecord;
 
 use super::RequestContext;
@@ -164,4 +165,50 @@ pub enum PendingNetworkOp {
         user_id: u128,
         zid_endpoint: String,
     },
+
+    // =========================================================================
+    // Machine enrollment approval operations
+    // =========================================================================
+    /// Post a new enrollment request to ZID server
+    SubmitEnrollmentRequest {
+        ctx: RequestContext,
+        enrollment: Box<PendingEnrollment>,
+    },
+    /// Fetch enrollment requests awaiting a decision
+    ListPendingEnrollments {
+        ctx: RequestContext,
+    },
+    /// Post a signed enrollment decision to ZID server
+    SubmitEnrollmentDecision {
+        ctx: RequestContext,
+    },
+    /// Fetch the decision on this machine's enrollment request
+    FetchEnrollmentStatus {
+        ctx: RequestContext,
+        user_id: u128,
+        local: Box<LocalEnrollment>,
+    },
+}
+
+impl PendingNetworkOp {
+    /// PID of the client awaiting the response.
+    pub fn client_pid(&self) -> u32 {
+        match self {
+            PendingNetworkOp::RequestZidChallenge { ctx, .. } |
+            PendingNetworkOp::SubmitZidLogin { ctx, .. } |
+            PendingNetworkOp::SubmitEmailToZid { ctx, .. } |
+            PendingNetworkOp::SubmitZidEnroll { ctx, .. } |
+            PendingNetworkOp::RequestZidChallengeAfterEnroll { ctx, .. } |
+            PendingNetworkOp::SubmitZidLoginAfterEnroll { ctx, .. } |
+            PendingNetworkOp::SubmitZidEnrollForCombined { ctx, .. } |
+            PendingNetworkOp::RequestZidChallengeForCombined { ctx, .. } |
+            PendingNetworkOp::SubmitZidLoginForCombined { ctx, .. } |
+            PendingNetworkOp::SubmitZidRefresh { ctx, .. } |
+            PendingNetworkOp::SubmitZidEmailLogin { ctx, .. } |
+            PendingNetworkOp::SubmitEnrollmentRequest { ctx, .. } |
+            PendingNetworkOp::ListPendingEnrollments { ctx } |
+            PendingNetworkOp::SubmitEnrollmentDecision { ctx } |
+            PendingNetworkOp::FetchEnrollmentStatus { ctx, .. } => ctx.client_pid,
+        }
+    }
 }
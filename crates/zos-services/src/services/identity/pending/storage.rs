@@ -4,7 +4,7 @@ extern crate alloc;
 
 use alloc::string::String;
 use alloc::vec::Vec;
-use zos_identity::ipc::{NeuralKeyGenerated, ZidTokens};
+use zos_identity::ipc::{LoginItem, NeuralKeyGenerated, ZidTokens};
 use zos_identity::keystore::{CredentialType, MachineKeyRecord};
 
 use super::RequestContext;
@@ -288,6 +288,18 @@ pub enum PendingStorageOp {
         user_id: u128,
         json_bytes: Vec<u8>,
     },
+    /// Read preferences before replacing the login items
+    ReadPreferencesForLoginItems {
+        ctx: RequestContext,
+        user_id: u128,
+        items: Vec<LoginItem>,
+    },
+    /// Write updated preferences with new login items
+    WritePreferencesForLoginItems {
+        ctx: RequestContext,
+        user_id: u128,
+        json_bytes: Vec<u8>,
+    },
     /// Read preferences before ZID login to get default_machine_id
     ReadPreferencesForZidLogin {
         ctx: RequestContext,
@@ -315,6 +327,14 @@ pub enum PendingStorageOp {
         tokens: ZidTokens,
         json_bytes: Vec<u8>,
     },
+
+    // =========================================================================
+    // Service state checkpoint
+    // =========================================================================
+    /// Read the service checkpoint at startup
+    RestoreState,
+    /// Write the service checkpoint
+    CheckpointState,
 }
 
 impl PendingStorageOp {
@@ -349,9 +369,11 @@ impl PendingStorageOp {
             PendingStorageOp::ReadMachineKeyForZidEnroll { .. } |
             PendingStorageOp::ReadIdentityPreferences { .. } |
             PendingStorageOp::ReadPreferencesForUpdate { .. } |
+            PendingStorageOp::ReadPreferencesForLoginItems { .. } |
             PendingStorageOp::ReadPreferencesForDefaultMachine { .. } |
             PendingStorageOp::ReadPreferencesForZidLogin { .. } |
-            PendingStorageOp::ReadZidSessionForRefresh { .. } => ExpectedVfsResponse::Read,
+            PendingStorageOp::ReadZidSessionForRefresh { .. } |
+            PendingStorageOp::RestoreState => ExpectedVfsResponse::Read,
 
             // WRITE response operations
             PendingStorageOp::WriteKeyStore { .. } |
@@ -365,9 +387,11 @@ impl PendingStorageOp {
             PendingStorageOp::WriteZidEnrollSession { .. } |
             PendingStorageOp::WriteZidEmailLoginSession { .. } |
             PendingStorageOp::WritePreferences { .. } |
+            PendingStorageOp::WritePreferencesForLoginItems { .. } |
             PendingStorageOp::WritePreferencesForDefaultMachine { .. } |
             PendingStorageOp::WritePreferencesForDefaultMachineRetry { .. } |
-            PendingStorageOp::WriteRefreshedZidSession { .. } => ExpectedVfsResponse::Write,
+            PendingStorageOp::WriteRefreshedZidSession { .. } |
+            PendingStorageOp::CheckpointState => ExpectedVfsResponse::Write,
 
             // READDIR response operations
             PendingStorageOp::ListMachineKeys { .. } => ExpectedVfsResponse::Readdir,
@@ -377,4 +401,54 @@ impl PendingStorageOp {
             PendingStorageOp::DeleteZidSession { .. } => ExpectedVfsResponse::Unlink,
         }
     }
+
+    /// PID of the client awaiting the response (`None` for the service's
+    /// own checkpoint operations).
+    pub fn client_pid(&self) -> Option<u32> {
+        match self {
+            PendingStorageOp::CheckIdentityDirectory { ctx, .. } |
+            PendingStorageOp::CreateIdentityDirectory { ctx, .. } |
+            PendingStorageOp::CreateIdentityDirectoryComplete { ctx, .. } |
+            PendingStorageOp::CreateDerivedUserDirectory { ctx, .. } |
+            PendingStorageOp::CheckKeyExists { ctx, .. } |
+            PendingStorageOp::WriteKeyStore { ctx, .. } |
+            PendingStorageOp::GetIdentityKey { ctx, .. } |
+            PendingStorageOp::ReadIdentityForRecovery { ctx, .. } |
+            PendingStorageOp::WriteRecoveredKeyStore { ctx, .. } |
+            PendingStorageOp::ReadIdentityForMachine { ctx, .. } |
+            PendingStorageOp::WriteMachineKey { ctx, .. } |
+            PendingStorageOp::ListMachineKeys { ctx, .. } |
+            PendingStorageOp::ReadMachineKey { ctx, .. } |
+            PendingStorageOp::DeleteMachineKey { ctx, .. } |
+            PendingStorageOp::ReadMachineForRotate { ctx, .. } |
+            PendingStorageOp::WriteRotatedMachineKey { ctx, .. } |
+            PendingStorageOp::ReadSingleMachineKey { ctx, .. } |
+            PendingStorageOp::ReadCredentialsForAttach { ctx, .. } |
+            PendingStorageOp::GetCredentials { ctx, .. } |
+            PendingStorageOp::ReadCredentialsForUnlink { ctx, .. } |
+            PendingStorageOp::WriteUnlinkedCredential { ctx, .. } |
+            PendingStorageOp::WriteEmailCredential { ctx, .. } |
+            PendingStorageOp::CreateCredentialsDirectory { ctx, .. } |
+            PendingStorageOp::WriteEmailCredentialRetry { ctx, .. } |
+            PendingStorageOp::ReadMachineKeyForZidLogin { ctx, .. } |
+            PendingStorageOp::WriteZidSession { ctx, .. } |
+            PendingStorageOp::ReadMachineKeyForZidEnroll { ctx, .. } |
+            PendingStorageOp::WriteZidEnrollSession { ctx, .. } |
+            PendingStorageOp::DeleteZidSession { ctx, .. } |
+            PendingStorageOp::WriteZidEmailLoginSession { ctx, .. } |
+            PendingStorageOp::ReadIdentityPreferences { ctx, .. } |
+            PendingStorageOp::ReadPreferencesForUpdate { ctx, .. } |
+            PendingStorageOp::ReadPreferencesForLoginItems { ctx, .. } |
+            PendingStorageOp::WritePreferences { ctx, .. } |
+            PendingStorageOp::WritePreferencesForLoginItems { ctx, .. } |
+            PendingStorageOp::ReadPreferencesForDefaultMachine { ctx, .. } |
+            PendingStorageOp::WritePreferencesForDefaultMachine { ctx, .. } |
+            PendingStorageOp::CreateIdentityDirForPreferences { ctx, .. } |
+            PendingStorageOp::WritePreferencesForDefaultMachineRetry { ctx, .. } |
+            PendingStorageOp::ReadPreferencesForZidLogin { ctx, .. } |
+            PendingStorageOp::ReadZidSessionForRefresh { ctx, .. } |
+            PendingStorageOp::WriteRefreshedZidSession { ctx, .. } => Some(ctx.client_pid),
+            PendingStorageOp::RestoreState | PendingStorageOp::CheckpointState => None,
+        }
+    }
 }
@@ -9,17 +9,24 @@ use zos_apps::syscall;
 use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
+use zos_identity::backup::{ShardBackupStatus, ShardSetReport};
 use zos_identity::error::{CredentialError, ZidError};
 use zos_identity::ipc::{
-    AttachEmailResponse, CreateMachineKeyAndEnrollResponse, CreateMachineKeyResponse,
+    AttachEmailResponse, CompleteEnrollmentResponse, CreateMachineKeyAndEnrollResponse,
+    CreateMachineKeyResponse, DecideEnrollmentResponse, ExportShardResponse,
     GenerateNeuralKeyResponse, GetCredentialsResponse, GetIdentityKeyResponse,
-    GetMachineKeyResponse, ListMachineKeysResponse, MachineKeyAndTokens, RecoverNeuralKeyResponse,
+    GetKeyFingerprintResponse, KeyFingerprint,
+    GetMachineKeyResponse, GetShardBackupStatusResponse, ListMachineKeysResponse,
+    ListPendingEnrollmentsResponse, MachineKeyAndTokens, MarkShardBackedUpResponse,
+    PendingEnrollment, RecoverNeuralKeyResponse, RequestEnrollmentResponse,
     RevokeMachineKeyResponse, RotateMachineKeyResponse, UnlinkCredentialResponse,
-    ZidEnrollMachineResponse, ZidLoginResponse, ZidTokens,
+    VerifyShardsResponse, ZidEnrollMachineResponse, ZidLoginResponse, ZidTokens,
 };
 use zos_identity::keystore::{LinkedCredential, LocalKeyStore, MachineKeyRecord};
 use zos_identity::KeyError;
-use zos_process::{identity_cred, identity_key, identity_machine, identity_zid};
+use zos_process::{
+    identity_cred, identity_enroll, identity_key, identity_machine, identity_query, identity_zid,
+};
 
 /// Send a generic serialized response to a specific PID via debug channel routing.
 pub fn send_response_to_pid<T: serde::Serialize>(
@@ -141,6 +148,106 @@ pub fn send_recover_key_error(
     send_recover_key_response(client_pid, cap_slots, Err(error))
 }
 
+// =============================================================================
+// Shard backup responses
+// =============================================================================
+
+/// Send export shard response (success or error).
+pub fn send_export_shard_response(
+    client_pid: u32,
+    cap_slots: &[u32],
+    result: Result<String, KeyError>,
+) -> Result<(), AppError> {
+    let response = ExportShardResponse { result };
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        identity_key::MSG_EXPORT_SHARD_RESPONSE,
+        &response,
+    )
+}
+
+/// Send export shard error response.
+pub fn send_export_shard_error(
+    client_pid: u32,
+    cap_slots: &[u32],
+    error: KeyError,
+) -> Result<(), AppError> {
+    send_export_shard_response(client_pid, cap_slots, Err(error))
+}
+
+/// Send verify shards response (success or error).
+pub fn send_verify_shards_response(
+    client_pid: u32,
+    cap_slots: &[u32],
+    result: Result<ShardSetReport, KeyError>,
+) -> Result<(), AppError> {
+    let response = VerifyShardsResponse { result };
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        identity_key::MSG_VERIFY_SHARDS_RESPONSE,
+        &response,
+    )
+}
+
+/// Send verify shards error response.
+pub fn send_verify_shards_error(
+    client_pid: u32,
+    cap_slots: &[u32],
+    error: KeyError,
+) -> Result<(), AppError> {
+    send_verify_shards_response(client_pid, cap_slots, Err(error))
+}
+
+/// Send mark shard backed up response (success or error).
+pub fn send_mark_shard_backed_up_response(
+    client_pid: u32,
+    cap_slots: &[u32],
+    result: Result<ShardBackupStatus, KeyError>,
+) -> Result<(), AppError> {
+    let response = MarkShardBackedUpResponse { result };
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        identity_key::MSG_MARK_SHARD_BACKED_UP_RESPONSE,
+        &response,
+    )
+}
+
+/// Send mark shard backed up error response.
+pub fn send_mark_shard_backed_up_error(
+    client_pid: u32,
+    cap_slots: &[u32],
+    error: KeyError,
+) -> Result<(), AppError> {
+    send_mark_shard_backed_up_response(client_pid, cap_slots, Err(error))
+}
+
+/// Send shard backup status response (success or error).
+pub fn send_shard_backup_status_response(
+    client_pid: u32,
+    cap_slots: &[u32],
+    result: Result<ShardBackupStatus, KeyError>,
+) -> Result<(), AppError> {
+    let response = GetShardBackupStatusResponse { result };
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        identity_key::MSG_GET_SHARD_BACKUP_STATUS_RESPONSE,
+        &response,
+    )
+}
+
+/// Send shard backup status error response.
+pub fn send_shard_backup_status_error(
+    client_pid: u32,
+    cap_slots: &[u32],
+    error: KeyError,
+) -> Result<(), AppError> {
+    send_shard_backup_status_response(client_pid, cap_slots, Err(error))
+}
+
 /// Send get identity key response (success or error).
 pub fn send_get_identity_key_response(
     client_pid: u32,
@@ -174,6 +281,30 @@ pub fn send_get_identity_key_error(
     send_get_identity_key_response(client_pid, cap_slots, Err(error))
 }
 
+/// Send get key fingerprint response (success or error).
+pub fn send_get_key_fingerprint_response(
+    client_pid: u32,
+    cap_slots: &[u32],
+    result: Result<KeyFingerprint, KeyError>,
+) -> Result<(), AppError> {
+    let response = GetKeyFingerprintResponse { result };
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        identity_query::MSG_GET_KEY_FINGERPRINT_RESPONSE,
+        &response,
+    )
+}
+
+/// Send get key fingerprint error response.
+pub fn send_get_key_fingerprint_error(
+    client_pid: u32,
+    cap_slots: &[u32],
+    error: KeyError,
+) -> Result<(), AppError> {
+    send_get_key_fingerprint_response(client_pid, cap_slots, Err(error))
+}
+
 // =============================================================================
 // Machine Key responses
 // =============================================================================
@@ -681,6 +812,35 @@ pub fn send_set_default_machine_key_error(
     )
 }
 
+/// Send set login items success response.
+pub fn send_set_login_items_response(
+    client_pid: u32,
+    cap_slots: &[u32],
+    response: zos_identity::ipc::SetLoginItemsResponse,
+) -> Result<(), AppError> {
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        zos_process::identity_prefs::MSG_SET_LOGIN_ITEMS_RESPONSE,
+        &response,
+    )
+}
+
+/// Send set login items error response.
+pub fn send_set_login_items_error(
+    client_pid: u32,
+    cap_slots: &[u32],
+    error: KeyError,
+) -> Result<(), AppError> {
+    let response = zos_identity::ipc::SetLoginItemsResponse { result: Err(error) };
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        zos_process::identity_prefs::MSG_SET_LOGIN_ITEMS_RESPONSE,
+        &response,
+    )
+}
+
 // =============================================================================
 // Combined Machine Key + ZID Enrollment responses
 // =============================================================================
@@ -722,3 +882,103 @@ pub fn send_create_machine_key_and_enroll_error(
 ) -> Result<(), AppError> {
     send_create_machine_key_and_enroll_response(client_pid, cap_slots, Err(error))
 }
+
+// =============================================================================
+// Machine Enrollment Approval responses
+// =============================================================================
+
+/// Send request enrollment response (success or error).
+pub fn send_request_enrollment_response(
+    client_pid: u32,
+    cap_slots: &[u32],
+    result: Result<PendingEnrollment, ZidError>,
+) -> Result<(), AppError> {
+    let response = RequestEnrollmentResponse { result };
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        identity_enroll::MSG_REQUEST_ENROLLMENT_RESPONSE,
+        &response,
+    )
+}
+
+/// Send request enrollment error response.
+pub fn send_request_enrollment_error(
+    client_pid: u32,
+    cap_slots: &[u32],
+    error: ZidError,
+) -> Result<(), AppError> {
+    send_request_enrollment_response(client_pid, cap_slots, Err(error))
+}
+
+/// Send list pending enrollments response (success or error).
+pub fn send_list_pending_enrollments_response(
+    client_pid: u32,
+    cap_slots: &[u32],
+    result: Result<Vec<PendingEnrollment>, ZidError>,
+) -> Result<(), AppError> {
+    let response = ListPendingEnrollmentsResponse { result };
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        identity_enroll::MSG_LIST_PENDING_ENROLLMENTS_RESPONSE,
+        &response,
+    )
+}
+
+/// Send list pending enrollments error response.
+pub fn send_list_pending_enrollments_error(
+    client_pid: u32,
+    cap_slots: &[u32],
+    error: ZidError,
+) -> Result<(), AppError> {
+    send_list_pending_enrollments_response(client_pid, cap_slots, Err(error))
+}
+
+/// Send decide enrollment response (success or error).
+pub fn send_decide_enrollment_response(
+    client_pid: u32,
+    cap_slots: &[u32],
+    result: Result<(), ZidError>,
+) -> Result<(), AppError> {
+    let response = DecideEnrollmentResponse { result };
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        identity_enroll::MSG_DECIDE_ENROLLMENT_RESPONSE,
+        &response,
+    )
+}
+
+/// Send decide enrollment error response.
+pub fn send_decide_enrollment_error(
+    client_pid: u32,
+    cap_slots: &[u32],
+    error: ZidError,
+) -> Result<(), AppError> {
+    send_decide_enrollment_response(client_pid, cap_slots, Err(error))
+}
+
+/// Send complete enrollment response (success or error).
+pub fn send_complete_enrollment_response(
+    client_pid: u32,
+    cap_slots: &[u32],
+    result: Result<MachineKeyRecord, ZidError>,
+) -> Result<(), AppError> {
+    let response = CompleteEnrollmentResponse { result };
+    send_response_to_pid(
+        client_pid,
+        cap_slots,
+        identity_enroll::MSG_COMPLETE_ENROLLMENT_RESPONSE,
+        &response,
+    )
+}
+
+/// Send complete enrollment error response.
+pub fn send_complete_enrollment_error(
+    client_pid: u32,
+    cap_slots: &[u32],
+    error: ZidError,
+) -> Result<(), AppError> {
+    send_complete_enrollment_response(client_pid, cap_slots, Err(error))
+}
@@ -503,6 +503,25 @@ mod tests {
         assert_eq!(op.expected_response(), ExpectedVfsResponse::Mkdir);
     }
 
+    #[test]
+    fn test_login_items_update_is_read_then_write() {
+        let op = PendingStorageOp::ReadPreferencesForLoginItems {
+            ctx: RequestContext::new(10, vec![]),
+            user_id: 1,
+            items: vec![],
+        };
+        assert_eq!(op.expected_response(), ExpectedVfsResponse::Read);
+        assert_eq!(op.client_pid(), Some(10));
+
+        let op = PendingStorageOp::WritePreferencesForLoginItems {
+            ctx: RequestContext::new(10, vec![]),
+            user_id: 1,
+            json_bytes: vec![],
+        };
+        assert_eq!(op.expected_response(), ExpectedVfsResponse::Write);
+        assert_eq!(op.client_pid(), Some(10));
+    }
+
     #[test]
     fn test_type_based_matching_finds_correct_operation() {
         // Simulate concurrent operations of different types
@@ -585,4 +604,113 @@ mod tests {
             .map(|(k, _)| *k);
         assert_eq!(exists_key, Some(1));
     }
+
+    // =========================================================================
+    // State checkpoint tests
+    // =========================================================================
+
+    #[test]
+    fn test_checkpoint_tracks_requests_until_answered() {
+        use zos_apps::Message;
+        use zos_process::identity_key;
+
+        let mut service = IdentityService::default();
+        let request = Message::new(identity_key::MSG_GET_IDENTITY_KEY, 42, vec![], vec![]);
+
+        // Answered synchronously - nothing to track
+        service.track_client_request(&request);
+        assert!(service.client_requests.is_empty());
+
+        service.pending_vfs_ops.insert(
+            1,
+            PendingStorageOp::GetIdentityKey {
+                ctx: RequestContext::new(42, vec![]),
+            },
+        );
+        service.track_client_request(&request);
+        service.checkpoint.restore(None);
+        service.pump_checkpoint(0);
+        let interrupted = &service.checkpoint.state().interrupted;
+        assert_eq!(interrupted.len(), 1);
+        assert_eq!(interrupted[0].pid, 42);
+        assert_eq!(interrupted[0].response_tag, identity_key::MSG_GET_IDENTITY_KEY_RESPONSE);
+
+        // Response sent - the request drops out of the next checkpoint
+        service.pending_vfs_ops.clear();
+        service.pump_checkpoint(0);
+        assert!(service.client_requests.is_empty());
+        assert!(service.checkpoint.state().interrupted.is_empty());
+    }
+
+    #[test]
+    fn test_checkpoint_ops_match_vfs_responses() {
+        assert_eq!(
+            PendingStorageOp::RestoreState.expected_response(),
+            ExpectedVfsResponse::Read
+        );
+        assert_eq!(
+            PendingStorageOp::CheckpointState.expected_response(),
+            ExpectedVfsResponse::Write
+        );
+        assert_eq!(PendingStorageOp::CheckpointState.client_pid(), None);
+    }
+
+    // =========================================================================
+    // Enrollment approval tests
+    // =========================================================================
+
+    #[test]
+    fn test_enrollment_decision_verification() {
+        use crate::services::identity::handlers::enrollment::verify_decision;
+        use crate::services::identity::utils::bytes_to_hex;
+        use ed25519_dalek::{Signer, SigningKey};
+        use zos_identity::error::ZidError;
+        use zos_identity::ipc::{EnrollmentDecision, EnrollmentOutcome, PendingEnrollment};
+        use zos_identity::keystore::MachineKeyCapabilities;
+
+        let enrollment = PendingEnrollment {
+            request_id: 0xabc,
+            machine_id: 0xdef,
+            signing_public_key: [1; 32],
+            encryption_public_key: [2; 32],
+            machine_name: None,
+            capabilities: MachineKeyCapabilities::default(),
+            created_at: 0,
+            expires_at: 60_000,
+        };
+        let approver = SigningKey::from_bytes(&[7; 32]);
+        let sign = |outcome, enrollment: &PendingEnrollment| EnrollmentDecision {
+            request_id: enrollment.request_id,
+            outcome,
+            approver_machine_id: 5,
+            approver_signing_public_key: approver.verifying_key().to_bytes(),
+            decided_at: 1_000,
+            signature: bytes_to_hex(
+                &approver
+                    .sign(&enrollment.decision_message(outcome, 5, 1_000))
+                    .to_bytes(),
+            ),
+        };
+
+        let approval = sign(EnrollmentOutcome::Approve, &enrollment);
+        assert!(verify_decision(&enrollment, &approval).is_ok());
+
+        // Flipping the outcome invalidates the signature
+        let mut flipped = approval.clone();
+        flipped.outcome = EnrollmentOutcome::Reject;
+        assert!(matches!(
+            verify_decision(&enrollment, &flipped),
+            Err(ZidError::AuthenticationFailed)
+        ));
+
+        // An approval for different keys does not carry over
+        let mut swapped = enrollment.clone();
+        swapped.signing_public_key = [9; 32];
+        assert!(verify_decision(&swapped, &approval).is_err());
+
+        // Nor does one for another request
+        let mut other = enrollment.clone();
+        other.request_id = 0x123;
+        assert!(verify_decision(&enrollment, &sign(EnrollmentOutcome::Approve, &other)).is_err());
+    }
 }
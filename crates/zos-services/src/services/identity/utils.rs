@@ -29,6 +29,17 @@ pub fn bytes_to_hex(bytes: &[u8]) -> String {
     hex
 }
 
+/// Decode a hex string to bytes (`None` on odd length or non-hex input).
+pub fn hex_to_bytes(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
 /// Format a u128 as a UUID string (8-4-4-4-12 format with dashes).
 pub fn format_uuid(value: u128) -> String {
     let hex = format!("{:032x}", value);
@@ -38,6 +38,20 @@ impl IdentityService {
             vfs_msg::MSG_VFS_MKDIR_RESPONSE => self.handle_vfs_mkdir_response(msg),
             vfs_msg::MSG_VFS_READDIR_RESPONSE => self.handle_vfs_readdir_response(msg),
             vfs_msg::MSG_VFS_UNLINK_RESPONSE => self.handle_vfs_unlink_response(msg),
+            vfs_msg::MSG_VFS_SET_CALLER_USER_RESPONSE => {
+                // Nothing waits on it; a refusal only shows in the log
+                if let Ok(response) =
+                    serde_json::from_slice::<zos_vfs::ipc::SetCallerUserResponse>(&msg.data)
+                {
+                    if let Err(e) = response.result {
+                        syscall::debug(&format!(
+                            "IdentityService: VFS refused session user: {:?}",
+                            e
+                        ));
+                    }
+                }
+                Ok(())
+            }
             _ => {
                 syscall::debug(&format!(
                     "IdentityService: Unhandled VFS response tag 0x{:x}",
@@ -509,6 +523,41 @@ impl IdentityService {
                     ),
                 }
             }
+            PendingStorageOp::ReadPreferencesForLoginItems {
+                ctx,
+                user_id,
+                items,
+            } => {
+                let mut preferences = result
+                    .ok()
+                    .and_then(|data| {
+                        serde_json::from_slice::<zos_identity::ipc::IdentityPreferences>(&data).ok()
+                    })
+                    .unwrap_or_default();
+
+                preferences.login_items = items;
+
+                match serde_json::to_vec(&preferences) {
+                    Ok(json_bytes) => {
+                        let prefs_path =
+                            zos_identity::ipc::IdentityPreferences::storage_path(user_id);
+                        self.start_vfs_write(
+                            &prefs_path,
+                            &json_bytes,
+                            PendingStorageOp::WritePreferencesForLoginItems {
+                                ctx: RequestContext::new(ctx.client_pid, ctx.cap_slots),
+                                user_id,
+                                json_bytes: json_bytes.clone(),
+                            },
+                        )
+                    }
+                    Err(_) => response::send_set_login_items_error(
+                        ctx.client_pid,
+                        &ctx.cap_slots,
+                        KeyError::StorageError("Serialization failed".into()),
+                    ),
+                }
+            }
             PendingStorageOp::ReadPreferencesForZidLogin {
                 ctx,
                 user_id,
@@ -570,6 +619,7 @@ impl IdentityService {
             PendingStorageOp::WriteZidSession { ctx, .. } |
             PendingStorageOp::WriteZidEnrollSession { ctx, .. } |
             PendingStorageOp::WritePreferences { ctx, .. } |
+            PendingStorageOp::WritePreferencesForLoginItems { ctx, .. } |
             PendingStorageOp::WritePreferencesForDefaultMachine { ctx, .. } |
             PendingStorageOp::WritePreferencesForDefaultMachineRetry { ctx, .. } |
             PendingStorageOp::WriteRefreshedZidSession { ctx, .. } |
@@ -589,6 +639,13 @@ impl IdentityService {
                     "State machine error: unexpected VFS read result for non-read operation".into()
                 ))
             }
+            PendingStorageOp::RestoreState => self.finish_state_restore(result),
+            PendingStorageOp::CheckpointState => {
+                syscall::debug("IdentityService: STATE_MACHINE_ERROR - unexpected VFS read result for checkpoint op");
+                Err(AppError::Internal(
+                    "State machine error: unexpected VFS read result for checkpoint operation".into()
+                ))
+            }
         }
     }
 
@@ -756,7 +813,9 @@ impl IdentityService {
                     }
                 }
             }
-            PendingStorageOp::WriteZidSession { ctx, tokens, .. } => {
+            PendingStorageOp::WriteZidSession { ctx, user_id, tokens, .. } => {
+                // Signed in either way: files are now accessed as this user
+                self.set_vfs_session_user(Some(user_id));
                 match result {
                     Ok(()) => {
                         syscall::debug("IdentityService: ZID session stored successfully via VFS");
@@ -775,7 +834,9 @@ impl IdentityService {
                     }
                 }
             }
-            PendingStorageOp::WriteZidEnrollSession { ctx, tokens, .. } => {
+            PendingStorageOp::WriteZidEnrollSession { ctx, user_id, tokens, .. } => {
+                // Signed in either way: files are now accessed as this user
+                self.set_vfs_session_user(Some(user_id));
                 match result {
                     Ok(()) => {
                         syscall::debug("IdentityService: ZID enroll session stored successfully via VFS");
@@ -863,6 +924,27 @@ impl IdentityService {
                     }
                 }
             }
+            PendingStorageOp::WritePreferencesForLoginItems { ctx, .. } => {
+                match result {
+                    Ok(()) => {
+                        syscall::debug("IdentityService: Login items stored successfully via VFS");
+                        let resp = zos_identity::ipc::SetLoginItemsResponse { result: Ok(()) };
+                        response::send_set_login_items_response(ctx.client_pid, &ctx.cap_slots, resp)
+                    }
+                    Err(e) => {
+                        // Rule 9: Include operation and result type in error message
+                        syscall::debug(&format!(
+                            "IdentityService: WritePreferencesForLoginItems failed - op=set_login_items, error={}",
+                            e
+                        ));
+                        response::send_set_login_items_error(
+                            ctx.client_pid,
+                            &ctx.cap_slots,
+                            KeyError::StorageError(format!("VFS write failed for login items: {}", e)),
+                        )
+                    }
+                }
+            }
             PendingStorageOp::WriteRefreshedZidSession { ctx, tokens, .. } => {
                 match result {
                     Ok(()) => {
@@ -882,7 +964,9 @@ impl IdentityService {
                     }
                 }
             }
-            PendingStorageOp::WriteZidEmailLoginSession { ctx, tokens, .. } => {
+            PendingStorageOp::WriteZidEmailLoginSession { ctx, user_id, tokens, .. } => {
+                // Signed in either way: files are now accessed as this user
+                self.set_vfs_session_user(Some(user_id));
                 match result {
                     Ok(()) => {
                         syscall::debug("IdentityService: ZID email login session stored successfully via VFS");
@@ -921,6 +1005,7 @@ impl IdentityService {
             PendingStorageOp::ReadMachineKeyForZidEnroll { ctx, .. } |
             PendingStorageOp::ReadIdentityPreferences { ctx, .. } |
             PendingStorageOp::ReadPreferencesForUpdate { ctx, .. } |
+            PendingStorageOp::ReadPreferencesForLoginItems { ctx, .. } |
             PendingStorageOp::ReadPreferencesForDefaultMachine { ctx, .. } |
             PendingStorageOp::ReadPreferencesForZidLogin { ctx, .. } |
             PendingStorageOp::ReadZidSessionForRefresh { ctx, .. } |
@@ -939,6 +1024,13 @@ impl IdentityService {
                     "State machine error: unexpected VFS write result for non-write operation".into()
                 ))
             }
+            PendingStorageOp::CheckpointState => self.finish_state_checkpoint(result),
+            PendingStorageOp::RestoreState => {
+                syscall::debug("IdentityService: STATE_MACHINE_ERROR - unexpected VFS write result for checkpoint op");
+                Err(AppError::Internal(
+                    "State machine error: unexpected VFS write result for checkpoint operation".into()
+                ))
+            }
         }
     }
 
@@ -1026,10 +1118,12 @@ impl IdentityService {
             PendingStorageOp::WriteZidEnrollSession { ctx, .. } |
             PendingStorageOp::ReadIdentityPreferences { ctx, .. } |
             PendingStorageOp::ReadPreferencesForUpdate { ctx, .. } |
+            PendingStorageOp::ReadPreferencesForLoginItems { ctx, .. } |
             PendingStorageOp::ReadPreferencesForDefaultMachine { ctx, .. } |
             PendingStorageOp::ReadPreferencesForZidLogin { ctx, .. } |
             PendingStorageOp::ReadZidSessionForRefresh { ctx, .. } |
             PendingStorageOp::WritePreferences { ctx, .. } |
+            PendingStorageOp::WritePreferencesForLoginItems { ctx, .. } |
             PendingStorageOp::WritePreferencesForDefaultMachine { ctx, .. } |
             PendingStorageOp::WritePreferencesForDefaultMachineRetry { ctx, .. } |
             PendingStorageOp::WriteRefreshedZidSession { ctx, .. } |
@@ -1050,6 +1144,12 @@ impl IdentityService {
                     "State machine error: unexpected VFS exists result for non-exists operation".into()
                 ))
             }
+            PendingStorageOp::RestoreState | PendingStorageOp::CheckpointState => {
+                syscall::debug("IdentityService: STATE_MACHINE_ERROR - unexpected VFS exists result for checkpoint op");
+                Err(AppError::Internal(
+                    "State machine error: unexpected VFS exists result for checkpoint operation".into()
+                ))
+            }
         }
     }
 
@@ -1236,10 +1336,12 @@ impl IdentityService {
             PendingStorageOp::WriteZidEnrollSession { ctx, .. } |
             PendingStorageOp::ReadIdentityPreferences { ctx, .. } |
             PendingStorageOp::ReadPreferencesForUpdate { ctx, .. } |
+            PendingStorageOp::ReadPreferencesForLoginItems { ctx, .. } |
             PendingStorageOp::ReadPreferencesForDefaultMachine { ctx, .. } |
             PendingStorageOp::ReadPreferencesForZidLogin { ctx, .. } |
             PendingStorageOp::ReadZidSessionForRefresh { ctx, .. } |
             PendingStorageOp::WritePreferences { ctx, .. } |
+            PendingStorageOp::WritePreferencesForLoginItems { ctx, .. } |
             PendingStorageOp::WritePreferencesForDefaultMachine { ctx, .. } |
             PendingStorageOp::WritePreferencesForDefaultMachineRetry { ctx, .. } |
             PendingStorageOp::WriteRefreshedZidSession { ctx, .. } |
@@ -1255,6 +1357,12 @@ impl IdentityService {
                     "State machine error: unexpected VFS mkdir result for non-mkdir operation".into()
                 ))
             }
+            PendingStorageOp::RestoreState | PendingStorageOp::CheckpointState => {
+                syscall::debug("IdentityService: STATE_MACHINE_ERROR - unexpected VFS mkdir result for checkpoint op");
+                Err(AppError::Internal(
+                    "State machine error: unexpected VFS mkdir result for checkpoint operation".into()
+                ))
+            }
         }
     }
 
@@ -1373,10 +1481,12 @@ impl IdentityService {
             PendingStorageOp::WriteZidEnrollSession { ctx, .. } |
             PendingStorageOp::ReadIdentityPreferences { ctx, .. } |
             PendingStorageOp::ReadPreferencesForUpdate { ctx, .. } |
+            PendingStorageOp::ReadPreferencesForLoginItems { ctx, .. } |
             PendingStorageOp::ReadPreferencesForDefaultMachine { ctx, .. } |
             PendingStorageOp::ReadPreferencesForZidLogin { ctx, .. } |
             PendingStorageOp::ReadZidSessionForRefresh { ctx, .. } |
             PendingStorageOp::WritePreferences { ctx, .. } |
+            PendingStorageOp::WritePreferencesForLoginItems { ctx, .. } |
             PendingStorageOp::WritePreferencesForDefaultMachine { ctx, .. } |
             PendingStorageOp::WritePreferencesForDefaultMachineRetry { ctx, .. } |
             PendingStorageOp::WriteRefreshedZidSession { ctx, .. } |
@@ -1396,6 +1506,12 @@ impl IdentityService {
                     "State machine error: unexpected VFS readdir result for non-readdir operation".into()
                 ))
             }
+            PendingStorageOp::RestoreState | PendingStorageOp::CheckpointState => {
+                syscall::debug("IdentityService: STATE_MACHINE_ERROR - unexpected VFS readdir result for checkpoint op");
+                Err(AppError::Internal(
+                    "State machine error: unexpected VFS readdir result for checkpoint operation".into()
+                ))
+            }
         }
     }
 
@@ -1419,6 +1535,7 @@ impl IdentityService {
                 }
             }
             PendingStorageOp::DeleteZidSession { ctx } => {
+                self.set_vfs_session_user(None);
                 // Session delete - success even if file didn't exist (already logged out)
                 if result.is_ok() {
                     syscall::debug("IdentityService: ZID session deleted successfully via VFS");
@@ -1457,10 +1574,12 @@ impl IdentityService {
             PendingStorageOp::WriteZidEnrollSession { ctx, .. } |
             PendingStorageOp::ReadIdentityPreferences { ctx, .. } |
             PendingStorageOp::ReadPreferencesForUpdate { ctx, .. } |
+            PendingStorageOp::ReadPreferencesForLoginItems { ctx, .. } |
             PendingStorageOp::ReadPreferencesForDefaultMachine { ctx, .. } |
             PendingStorageOp::ReadPreferencesForZidLogin { ctx, .. } |
             PendingStorageOp::ReadZidSessionForRefresh { ctx, .. } |
             PendingStorageOp::WritePreferences { ctx, .. } |
+            PendingStorageOp::WritePreferencesForLoginItems { ctx, .. } |
             PendingStorageOp::WritePreferencesForDefaultMachine { ctx, .. } |
             PendingStorageOp::WritePreferencesForDefaultMachineRetry { ctx, .. } |
             PendingStorageOp::WriteRefreshedZidSession { ctx, .. } |
@@ -1478,6 +1597,12 @@ impl IdentityService {
                     "State machine error: unexpected VFS unlink result for non-unlink operation".into()
                 ))
             }
+            PendingStorageOp::RestoreState | PendingStorageOp::CheckpointState => {
+                syscall::debug("IdentityService: STATE_MACHINE_ERROR - unexpected VFS unlink result for checkpoint op");
+                Err(AppError::Internal(
+                    "State machine error: unexpected VFS unlink result for checkpoint operation".into()
+                ))
+            }
         }
     }
 }
@@ -24,6 +24,18 @@ impl IdentityService {
     //
     // All storage operations route through VFS Service (PID 4) via IPC.
 
+    /// Tell VFS which user applications act as, or that nobody is signed in.
+    ///
+    /// Fire-and-forget: no pending operation is tracked for the response.
+    pub fn set_vfs_session_user(&self, user_id: Option<u128>) {
+        if let Err(e) = async_client::send_set_caller_user_request(user_id) {
+            syscall::debug(&format!(
+                "IdentityService: Failed to set VFS session user: {:?}",
+                e
+            ));
+        }
+    }
+
     /// Start async VFS read and track the pending operation.
     /// Uses VFS IPC instead of direct storage syscalls per Invariant 31.
     ///
@@ -11,8 +11,9 @@ use alloc::string::String;
 use alloc::vec::Vec;
 use zos_apps::syscall;
 use zos_apps::{AppContext, AppError, Message};
-use zos_process::keystore_result;
+use zos_ipc::storage::ResultKind;
 use zos_ipc::keystore_svc;
+use zos_ipc::request::CANCEL_ALL;
 
 use super::types::{
     KeystoreDeleteRequest, KeystoreDeleteResponse, KeystoreError, KeystoreExistsRequest,
@@ -20,9 +21,12 @@ use super::types::{
     KeystoreReadResponse, KeystoreWriteRequest, KeystoreWriteResponse,
 };
 use super::{
-    validate_key, result_type_name, ClientContext, KeystoreService, PendingOp, MAX_CONTENT_SIZE,
+    validate_key, ClientContext, KeystoreService, PendingOp, MAX_CONTENT_SIZE,
 };
 
+/// Error a stuck operation fails with.
+pub const STUCK_OP_ERROR: &str = "Operation timed out";
+
 impl KeystoreService {
     // =========================================================================
     // Request handlers (start async operations)
@@ -276,6 +280,72 @@ impl KeystoreService {
         )
     }
 
+    /// Handle MSG_CANCEL_REQUEST - drop the sender's in-flight requests
+    ///
+    /// Payload: [request_tag: u32], 0 for every request. The keystore HAL
+    /// cannot abort a request, so a write may still land; its result is
+    /// then dropped as an unknown request_id.
+    pub fn handle_cancel(&mut self, msg: &Message) -> Result<(), AppError> {
+        if msg.data.len() < 4 {
+            syscall::debug("KeystoreService: cancel request too short");
+            return Ok(());
+        }
+        let tag = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
+
+        let cancelled: Vec<u32> = self
+            .pending_ops
+            .iter()
+            .filter(|(_, op)| {
+                let (ctx, response_tag) = op.client_reply();
+                ctx.pid == msg.from_pid
+                    && (tag == CANCEL_ALL || response_tag == tag.wrapping_add(1))
+            })
+            .map(|(&request_id, _)| request_id)
+            .collect();
+
+        for request_id in cancelled {
+            let Some(op) = self.pending_ops.remove(&request_id) else {
+                continue;
+            };
+            let (ctx, response_tag) = op.client_reply();
+            syscall::debug(&format!(
+                "KeystoreService: Cancelled request_id {} for PID {}",
+                request_id, ctx.pid
+            ));
+            // Every keystore response is `{ result }`, so the error form is
+            // the same whichever operation was cancelled
+            let response = KeystoreWriteResponse {
+                result: Err(KeystoreError::Cancelled),
+            };
+            self.send_response(ctx, response_tag, &response)?;
+        }
+        Ok(())
+    }
+
+    /// Watchdog timer fired: fail the operations pending since the last
+    /// firing.
+    ///
+    /// The keystore HAL cannot abort a request, so each client is answered
+    /// here and a result that turns up later is dropped as unknown.
+    pub fn fail_stuck_ops(&mut self) -> Result<(), AppError> {
+        let stuck = self.watchdog.tick(self.pending_ops.keys().copied());
+        for request_id in stuck {
+            let Some(op) = self.pending_ops.remove(&request_id) else {
+                continue;
+            };
+            let (ctx, response_tag) = op.client_reply();
+            syscall::debug(&format!(
+                "KeystoreService: request_id {} for PID {} is stuck, failing it",
+                request_id, ctx.pid
+            ));
+            let response = KeystoreWriteResponse {
+                result: Err(KeystoreError::StorageError(String::from(STUCK_OP_ERROR))),
+            };
+            self.send_response(ctx, response_tag, &response)?;
+        }
+        Ok(())
+    }
+
     // =========================================================================
     // Result handlers
     // =========================================================================
@@ -285,28 +355,28 @@ impl KeystoreService {
         &self,
         ctx: &ClientContext,
         key: &str,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         let response = match result_type {
-            keystore_result::READ_OK => KeystoreReadResponse {
+            ResultKind::ReadOk => KeystoreReadResponse {
                 result: Ok(data.to_vec()),
             },
-            keystore_result::NOT_FOUND => KeystoreReadResponse {
+            ResultKind::NotFound => KeystoreReadResponse {
                 result: Err(KeystoreError::NotFound),
             },
             _ => {
                 syscall::debug(&format!(
                     "KeystoreService: read {} failed with unexpected result: {} ({})",
                     key,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 KeystoreReadResponse {
                     result: Err(KeystoreError::StorageError(format!(
                         "Read failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     ))),
                 }
             }
@@ -319,10 +389,10 @@ impl KeystoreService {
         &self,
         ctx: &ClientContext,
         key: &str,
-        result_type: u8,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
         let response = match result_type {
-            keystore_result::WRITE_OK => {
+            ResultKind::WriteOk => {
                 syscall::debug(&format!("KeystoreService: write {} completed", key));
                 KeystoreWriteResponse { result: Ok(()) }
             }
@@ -330,14 +400,14 @@ impl KeystoreService {
                 syscall::debug(&format!(
                     "KeystoreService: write {} failed with unexpected result: {} ({})",
                     key,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 KeystoreWriteResponse {
                     result: Err(KeystoreError::StorageError(format!(
                         "Write failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     ))),
                 }
             }
@@ -350,14 +420,14 @@ impl KeystoreService {
         &self,
         ctx: &ClientContext,
         key: &str,
-        result_type: u8,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
         let response = match result_type {
-            keystore_result::WRITE_OK => {
+            ResultKind::WriteOk => {
                 syscall::debug(&format!("KeystoreService: delete {} completed", key));
                 KeystoreDeleteResponse { result: Ok(()) }
             }
-            keystore_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 // Delete of non-existent key is still success
                 syscall::debug(&format!(
                     "KeystoreService: delete {} - key not found (OK)",
@@ -369,14 +439,14 @@ impl KeystoreService {
                 syscall::debug(&format!(
                     "KeystoreService: delete {} failed with unexpected result: {} ({})",
                     key,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 KeystoreDeleteResponse {
                     result: Err(KeystoreError::StorageError(format!(
                         "Delete failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     ))),
                 }
             }
@@ -389,11 +459,11 @@ impl KeystoreService {
         &self,
         ctx: &ClientContext,
         key: &str,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         let response = match result_type {
-            keystore_result::EXISTS_OK => {
+            ResultKind::ExistsOk => {
                 let exists = !data.is_empty() && data[0] == 1;
                 syscall::debug(&format!(
                     "KeystoreService: exists {} = {}",
@@ -403,21 +473,21 @@ impl KeystoreService {
                     result: Ok(exists),
                 }
             }
-            keystore_result::NOT_FOUND => KeystoreExistsResponse {
+            ResultKind::NotFound => KeystoreExistsResponse {
                 result: Ok(false),
             },
             _ => {
                 syscall::debug(&format!(
                     "KeystoreService: exists {} failed with unexpected result: {} ({})",
                     key,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 KeystoreExistsResponse {
                     result: Err(KeystoreError::StorageError(format!(
                         "Exists check failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     ))),
                 }
             }
@@ -430,11 +500,11 @@ impl KeystoreService {
         &self,
         ctx: &ClientContext,
         prefix: &str,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         let response = match result_type {
-            keystore_result::LIST_OK => {
+            ResultKind::ListOk => {
                 // Data is JSON array of keys
                 match serde_json::from_slice::<Vec<String>>(data) {
                     Ok(keys) => {
@@ -459,7 +529,7 @@ impl KeystoreService {
                     }
                 }
             }
-            keystore_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 // No keys found with this prefix
                 KeystoreListResponse {
                     result: Ok(Vec::new()),
@@ -469,14 +539,14 @@ impl KeystoreService {
                 syscall::debug(&format!(
                     "KeystoreService: list {} failed with unexpected result: {} ({})",
                     prefix,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 KeystoreListResponse {
                     result: Err(KeystoreError::StorageError(format!(
                         "List failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     ))),
                 }
             }
@@ -484,3 +554,30 @@ impl KeystoreService {
         self.send_response(ctx, keystore_svc::MSG_KEYSTORE_LIST_RESPONSE, &response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fail_stuck_ops() {
+        let mut service = KeystoreService::default();
+        let read = |key: &str| PendingOp::Read {
+            ctx: ClientContext {
+                pid: 10,
+                reply_caps: Vec::new(),
+                correlation_id: None,
+            },
+            key: String::from(key),
+        };
+        service.pending_ops.insert(1, read("/keys/10/a"));
+        service.fail_stuck_ops().unwrap();
+        service.pending_ops.insert(2, read("/keys/10/b"));
+
+        // Pending at two firings in a row: failed
+        service.fail_stuck_ops().unwrap();
+        assert_eq!(service.pending_ops.keys().copied().collect::<Vec<_>>(), [2]);
+        service.fail_stuck_ops().unwrap();
+        assert!(service.pending_ops.is_empty());
+    }
+}
@@ -77,6 +77,7 @@
 //! - `MSG_KEYSTORE_DELETE (0xA004)`: Delete key
 //! - `MSG_KEYSTORE_EXISTS (0xA006)`: Check if key exists
 //! - `MSG_KEYSTORE_LIST (0xA008)`: List keys with prefix
+//! - `MSG_CANCEL_REQUEST (0x0010)`: Cancel the sender's in-flight requests
 
 extern crate alloc;
 
@@ -90,9 +91,9 @@ use alloc::vec::Vec;
 
 use crate::manifests::KEYSTORE_MANIFEST;
 use zos_apps::syscall;
-use zos_apps::{AppContext, AppError, AppManifest, ControlFlow, Message, ZeroApp};
-use zos_process::keystore_result;
-use zos_process::MSG_KEYSTORE_RESULT;
+use zos_apps::framework::DEFAULT_WATCHDOG_PERIOD_MS;
+use zos_apps::{AppContext, AppError, AppManifest, ControlFlow, Message, Watchdog, ZeroApp};
+use zos_process::{StorageResult, MSG_KEYSTORE_RESULT};
 use zos_ipc::keystore_svc;
 
 use types::KeystoreError;
@@ -122,12 +123,15 @@ pub const MAX_CONTENT_SIZE: usize = 1024 * 1024;
 /// Captures information needed to send responses:
 /// - `pid`: The client process ID
 /// - `reply_caps`: Capability slots for direct IPC reply
+/// - `correlation_id`: Echoed in the response
 #[derive(Clone, Debug)]
 pub struct ClientContext {
     /// Client process ID
     pub pid: u32,
     /// Reply capability slots (for direct IPC response)
     pub reply_caps: Vec<u32>,
+    /// Correlation ID the request carried
+    pub correlation_id: Option<u32>,
 }
 
 impl ClientContext {
@@ -136,6 +140,7 @@ impl ClientContext {
         Self {
             pid: msg.from_pid,
             reply_caps: msg.cap_slots.clone(),
+            correlation_id: msg.correlation_id,
         }
     }
 }
@@ -170,6 +175,19 @@ pub enum PendingOp {
     },
 }
 
+impl PendingOp {
+    /// The client waiting on this operation and the response tag it expects.
+    pub fn client_reply(&self) -> (&ClientContext, u32) {
+        match self {
+            PendingOp::Read { ctx, .. } => (ctx, keystore_svc::MSG_KEYSTORE_READ_RESPONSE),
+            PendingOp::Write { ctx, .. } => (ctx, keystore_svc::MSG_KEYSTORE_WRITE_RESPONSE),
+            PendingOp::Delete { ctx, .. } => (ctx, keystore_svc::MSG_KEYSTORE_DELETE_RESPONSE),
+            PendingOp::Exists { ctx, .. } => (ctx, keystore_svc::MSG_KEYSTORE_EXISTS_RESPONSE),
+            PendingOp::List { ctx, .. } => (ctx, keystore_svc::MSG_KEYSTORE_LIST_RESPONSE),
+        }
+    }
+}
+
 // =============================================================================
 // KeystoreService Application
 // =============================================================================
@@ -181,6 +199,8 @@ pub struct KeystoreService {
     registered: bool,
     /// Pending keystore operations: request_id -> operation context
     pending_ops: BTreeMap<u32, PendingOp>,
+    /// Finds keystore operations whose result never arrives
+    watchdog: Watchdog,
 }
 
 // =============================================================================
@@ -212,19 +232,6 @@ pub fn validate_key(key: &str) -> Result<(), KeystoreError> {
     Ok(())
 }
 
-/// Format a keystore result type as a human-readable string.
-pub fn result_type_name(result_type: u8) -> &'static str {
-    match result_type {
-        keystore_result::READ_OK => "READ_OK",
-        keystore_result::WRITE_OK => "WRITE_OK",
-        keystore_result::NOT_FOUND => "NOT_FOUND",
-        keystore_result::ERROR => "ERROR",
-        keystore_result::LIST_OK => "LIST_OK",
-        keystore_result::EXISTS_OK => "EXISTS_OK",
-        _ => "UNKNOWN",
-    }
-}
-
 impl KeystoreService {
     // =========================================================================
     // Keystore syscall helpers
@@ -247,7 +254,6 @@ impl KeystoreService {
 
         match syscall::keystore_read_async(key) {
             Ok(request_id) => {
-                let request_id = request_id as u32;
                 syscall::debug(&format!(
                     "KeystoreService: keystore_read_async({}) -> request_id={}",
                     key, request_id
@@ -280,7 +286,6 @@ impl KeystoreService {
 
         match syscall::keystore_write_async(key, value) {
             Ok(request_id) => {
-                let request_id = request_id as u32;
                 syscall::debug(&format!(
                     "KeystoreService: keystore_write_async({}, {} bytes) -> request_id={}",
                     key,
@@ -314,7 +319,6 @@ impl KeystoreService {
 
         match syscall::keystore_delete_async(key) {
             Ok(request_id) => {
-                let request_id = request_id as u32;
                 syscall::debug(&format!(
                     "KeystoreService: keystore_delete_async({}) -> request_id={}",
                     key, request_id
@@ -349,7 +353,6 @@ impl KeystoreService {
 
         match syscall::keystore_exists_async(key) {
             Ok(request_id) => {
-                let request_id = request_id as u32;
                 syscall::debug(&format!(
                     "KeystoreService: keystore_exists_async({}) -> request_id={}",
                     key, request_id
@@ -384,7 +387,6 @@ impl KeystoreService {
 
         match syscall::keystore_list_async(prefix) {
             Ok(request_id) => {
-                let request_id = request_id as u32;
                 syscall::debug(&format!(
                     "KeystoreService: keystore_list_async({}) -> request_id={}",
                     prefix, request_id
@@ -405,29 +407,27 @@ impl KeystoreService {
 
     /// Handle MSG_KEYSTORE_RESULT - async keystore operation completed
     fn handle_keystore_result(&mut self, _ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
-        // Parse keystore result
-        // Format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
-        if msg.data.len() < 9 {
-            syscall::debug("KeystoreService: keystore result too short");
-            return Ok(());
-        }
-
-        let request_id = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
-        let result_type = msg.data[4];
-        let data_len =
-            u32::from_le_bytes([msg.data[5], msg.data[6], msg.data[7], msg.data[8]]) as usize;
-        let data = if data_len > 0 && msg.data.len() >= 9 + data_len {
-            &msg.data[9..9 + data_len]
-        } else {
-            &[]
+        // Keystore results share the storage result envelope
+        let StorageResult {
+            request_id,
+            kind: result_type,
+            data,
+        } = match StorageResult::decode(&msg.data) {
+            Ok(result) => result,
+            Err(e) => {
+                syscall::debug(&format!(
+                    "KeystoreService: malformed keystore result: {:?}",
+                    e
+                ));
+                return Ok(());
+            }
         };
 
         syscall::debug(&format!(
-            "KeystoreService: keystore result request_id={}, type={} ({}), data_len={}",
+            "KeystoreService: keystore result request_id={}, type={}, data_len={}",
             request_id,
-            result_type,
-            result_type_name(result_type),
-            data_len
+            result_type.name(),
+            data.len()
         ));
 
         // Look up pending operation
@@ -481,6 +481,9 @@ impl KeystoreService {
                         "KeystoreService: Sending response via reply cap slot {} (tag 0x{:x})",
                         reply_slot, tag
                     ));
+                    // Keystore results received since the request replaced
+                    // its correlation ID
+                    syscall::set_correlation_id(ctx.correlation_id);
                     match syscall::send(reply_slot, tag, &data) {
                         Ok(()) => {
                             syscall::debug("KeystoreService: Response sent via reply cap");
@@ -560,6 +563,13 @@ impl ZeroApp for KeystoreService {
         self.registered = true;
 
         syscall::debug("KeystoreService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+
+        if let Some(slot) = ctx.input_endpoint {
+            if let Err(e) = self.watchdog.arm(slot, DEFAULT_WATCHDOG_PERIOD_MS) {
+                syscall::debug(&format!("KeystoreService: No watchdog: {}", e));
+            }
+        }
 
         Ok(())
     }
@@ -574,6 +584,10 @@ impl ZeroApp for KeystoreService {
             msg.tag, msg.from_pid
         ));
 
+        if self.watchdog.is_tick(&msg) {
+            return self.fail_stuck_ops();
+        }
+
         match msg.tag {
             MSG_KEYSTORE_RESULT => self.handle_keystore_result(ctx, &msg),
             keystore_svc::MSG_KEYSTORE_READ => self.handle_read(ctx, &msg),
@@ -581,6 +595,7 @@ impl ZeroApp for KeystoreService {
             keystore_svc::MSG_KEYSTORE_DELETE => self.handle_delete(ctx, &msg),
             keystore_svc::MSG_KEYSTORE_EXISTS => self.handle_exists(ctx, &msg),
             keystore_svc::MSG_KEYSTORE_LIST => self.handle_list(ctx, &msg),
+            zos_ipc::request::MSG_CANCEL_REQUEST => self.handle_cancel(&msg),
             _ => {
                 syscall::debug(&format!(
                     "KeystoreService: Unknown message tag 0x{:x}",
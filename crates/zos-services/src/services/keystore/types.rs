@@ -21,6 +21,8 @@ pub enum KeystoreError {
     StorageError(String),
     /// Too many pending operations
     ResourceExhausted,
+    /// Request was cancelled by the client before it completed
+    Cancelled,
 }
 
 // ============================================================================
@@ -136,4 +138,20 @@ mod tests {
         let parsed: KeystoreReadResponse = serde_json::from_str(&json).unwrap();
         assert!(matches!(parsed.result, Err(KeystoreError::NotFound)));
     }
+
+    #[test]
+    fn test_cancelled_error_reaches_client() {
+        // A cancel answers every request with the same error form; clients
+        // decode it with their own response types
+        let resp = KeystoreWriteResponse {
+            result: Err(KeystoreError::Cancelled),
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        let parsed: zos_vfs::client::keystore_async::KeystoreReadResponse =
+            serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            parsed.result,
+            Err(zos_vfs::client::keystore_async::KeystoreError::Cancelled)
+        ));
+    }
 }
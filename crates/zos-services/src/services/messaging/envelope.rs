@@ -0,0 +1,310 @@
+//! Message envelopes
+//!
+//! Every message is sealed separately for each key it is sent to, so the
+//! relay only ever sees envelopes it can't read. Keys are the X25519
+//! machine encryption keys IdentityService keeps in the keystore (ours)
+//! and the address book (contacts').
+//!
+//! The sender draws a fresh ephemeral key per envelope and derives the
+//! content key from two exchanges, one with each of its keys:
+//!
+//! ```text
+//! ikm  = X25519(ephemeral, to) || X25519(from, to)
+//! salt = ephemeral_pub || from_pub || to_pub
+//! key  = HKDF-SHA256(salt, ikm, "zos-messaging-v1")
+//! ```
+//!
+//! The ephemeral exchange gives each envelope its own key; the static one
+//! means only the holder of `from`'s secret can have sealed it, so a
+//! recipient who opens an envelope knows who sent it. Content is sealed
+//! with AES-256-GCM, authenticating the header fields along with it.
+//!
+//! Sealing takes its randomness from the caller so this module stays
+//! deterministic and free of platform dependencies.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload as AeadPayload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::services::contacts::store::parse_key;
+use crate::services::identity::utils::{bytes_to_hex, hex_to_bytes};
+
+/// Envelope format version written by [`seal`].
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Random bytes [`seal`] needs: ephemeral secret, nonce.
+pub const SEAL_RANDOM_BYTES: usize = KEY_LEN + NONCE_LEN;
+
+/// Largest ciphertext [`open`] accepts, in bytes.
+pub const MAX_CIPHERTEXT_LEN: usize = 64 * 1024;
+
+/// HKDF info string, binding keys to this protocol and version.
+const KDF_INFO: &[u8] = b"zos-messaging-v1";
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// What an envelope carries once opened.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Payload {
+    /// A message
+    Text {
+        body: String,
+        /// When the sender wrote it, ms since the Unix epoch
+        sent_at_ms: u64,
+    },
+    /// The recipient's acknowledgement of a message
+    Receipt {
+        /// ID of the message acknowledged
+        of: String,
+        status: ReceiptStatus,
+    },
+    /// An entry copied on another of the user's machines
+    Clipboard {
+        text: String,
+        /// When it was copied, ms since the Unix epoch
+        copied_at_ms: u64,
+    },
+}
+
+/// How far a message got.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptStatus {
+    /// Stored on one of the recipient's machines
+    Delivered,
+    /// Marked read by the recipient
+    Read,
+}
+
+/// A sealed payload, as posted to a relay.
+///
+/// Keys, nonce and ciphertext are lowercase hex.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope {
+    pub version: u8,
+    /// Message ID, shared by the envelopes of one message
+    pub id: String,
+    /// Sender's encryption key
+    pub from: String,
+    /// Recipient's encryption key
+    pub to: String,
+    /// Per-envelope ephemeral key
+    pub ephemeral: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl Envelope {
+    /// Header bytes authenticated with the content.
+    fn associated_data(&self) -> Vec<u8> {
+        let mut aad = Vec::with_capacity(KDF_INFO.len() + 1 + self.id.len() + 3 * KEY_LEN * 2);
+        aad.extend_from_slice(KDF_INFO);
+        aad.push(self.version);
+        for field in [&self.id, &self.from, &self.to, &self.ephemeral] {
+            aad.extend_from_slice(&(field.len() as u16).to_be_bytes());
+            aad.extend_from_slice(field.as_bytes());
+        }
+        aad
+    }
+}
+
+/// Public key of an X25519 secret.
+pub fn public_key(secret: &[u8; KEY_LEN]) -> [u8; KEY_LEN] {
+    PublicKey::from(&StaticSecret::from(*secret)).to_bytes()
+}
+
+/// Content key for one envelope, from the two shared secrets.
+fn content_key(
+    ephemeral_shared: &[u8; KEY_LEN],
+    static_shared: &[u8; KEY_LEN],
+    ephemeral: &[u8; KEY_LEN],
+    from: &[u8; KEY_LEN],
+    to: &[u8; KEY_LEN],
+) -> Aes256Gcm {
+    let mut ikm = [0u8; KEY_LEN * 2];
+    ikm[..KEY_LEN].copy_from_slice(ephemeral_shared);
+    ikm[KEY_LEN..].copy_from_slice(static_shared);
+    let mut salt = [0u8; KEY_LEN * 3];
+    salt[..KEY_LEN].copy_from_slice(ephemeral);
+    salt[KEY_LEN..KEY_LEN * 2].copy_from_slice(from);
+    salt[KEY_LEN * 2..].copy_from_slice(to);
+
+    let mut key = [0u8; KEY_LEN];
+    // 32 bytes is always a valid HKDF-SHA256 output length
+    let _ = Hkdf::<Sha256>::new(Some(&salt), &ikm).expand(KDF_INFO, &mut key);
+    ikm.fill(0);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    key.fill(0);
+    cipher
+}
+
+/// Seal `payload` from the holder of `sender_secret` to `recipient`.
+pub fn seal(
+    sender_secret: &[u8; KEY_LEN],
+    recipient: &[u8; KEY_LEN],
+    id: &str,
+    payload: &Payload,
+    random: &[u8; SEAL_RANDOM_BYTES],
+) -> Result<Envelope, String> {
+    let (ephemeral_secret, nonce) = random.split_at(KEY_LEN);
+    let mut ephemeral_bytes = [0u8; KEY_LEN];
+    ephemeral_bytes.copy_from_slice(ephemeral_secret);
+    let ephemeral_secret = StaticSecret::from(ephemeral_bytes);
+    ephemeral_bytes.fill(0);
+    let sender_secret = StaticSecret::from(*sender_secret);
+
+    let ephemeral = PublicKey::from(&ephemeral_secret).to_bytes();
+    let from = PublicKey::from(&sender_secret).to_bytes();
+    let to = PublicKey::from(*recipient);
+    let ephemeral_shared = ephemeral_secret.diffie_hellman(&to);
+    let static_shared = sender_secret.diffie_hellman(&to);
+    if !ephemeral_shared.was_contributory() || !static_shared.was_contributory() {
+        return Err(String::from("Recipient key is not a usable X25519 key"));
+    }
+
+    let mut envelope = Envelope {
+        version: ENVELOPE_VERSION,
+        id: String::from(id),
+        from: bytes_to_hex(&from),
+        to: bytes_to_hex(recipient),
+        ephemeral: bytes_to_hex(&ephemeral),
+        nonce: bytes_to_hex(nonce),
+        ciphertext: String::new(),
+    };
+    let plaintext =
+        serde_json::to_vec(payload).map_err(|e| format!("Payload serialization failed: {}", e))?;
+    let ciphertext = content_key(
+        ephemeral_shared.as_bytes(),
+        static_shared.as_bytes(),
+        &ephemeral,
+        &from,
+        recipient,
+    )
+    .encrypt(
+        Nonce::from_slice(nonce),
+        AeadPayload {
+            msg: &plaintext,
+            aad: &envelope.associated_data(),
+        },
+    )
+    .map_err(|_| String::from("Message encryption failed"))?;
+    envelope.ciphertext = bytes_to_hex(&ciphertext);
+    Ok(envelope)
+}
+
+/// Open an envelope sent to the holder of `recipient_secret`, returning the
+/// sender's key and the payload.
+pub fn open(
+    recipient_secret: &[u8; KEY_LEN],
+    envelope: &Envelope,
+) -> Result<([u8; KEY_LEN], Payload), String> {
+    if envelope.version != ENVELOPE_VERSION {
+        return Err(format!("Unsupported envelope version {}", envelope.version));
+    }
+    if envelope.ciphertext.len() > MAX_CIPHERTEXT_LEN * 2 {
+        return Err(format!(
+            "Envelope too large (max {} bytes)",
+            MAX_CIPHERTEXT_LEN
+        ));
+    }
+    let recipient_secret = StaticSecret::from(*recipient_secret);
+    let to = PublicKey::from(&recipient_secret).to_bytes();
+    if parse_key(&envelope.to) != Some(to) {
+        return Err(String::from("Envelope is for another key"));
+    }
+    let (Some(from), Some(ephemeral)) = (parse_key(&envelope.from), parse_key(&envelope.ephemeral))
+    else {
+        return Err(String::from("Envelope keys are not 64 hex digits"));
+    };
+    let nonce = hex_to_bytes(&envelope.nonce)
+        .filter(|nonce| nonce.len() == NONCE_LEN)
+        .ok_or_else(|| String::from("Envelope nonce is not 12 hex bytes"))?;
+    let ciphertext = hex_to_bytes(&envelope.ciphertext)
+        .ok_or_else(|| String::from("Envelope ciphertext is not hex"))?;
+
+    let ephemeral_shared = recipient_secret.diffie_hellman(&PublicKey::from(ephemeral));
+    let static_shared = recipient_secret.diffie_hellman(&PublicKey::from(from));
+    if !ephemeral_shared.was_contributory() || !static_shared.was_contributory() {
+        return Err(String::from("Envelope keys are not usable X25519 keys"));
+    }
+    let plaintext = content_key(
+        ephemeral_shared.as_bytes(),
+        static_shared.as_bytes(),
+        &ephemeral,
+        &from,
+        &to,
+    )
+    .decrypt(
+        Nonce::from_slice(&nonce),
+        AeadPayload {
+            msg: &ciphertext,
+            aad: &envelope.associated_data(),
+        },
+    )
+    .map_err(|_| String::from("Envelope failed authentication"))?;
+    let payload = serde_json::from_slice(&plaintext)
+        .map_err(|e| format!("Envelope payload unreadable: {}", e))?;
+    Ok((from, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: [u8; 32] = [1; 32];
+    const BOB: [u8; 32] = [2; 32];
+
+    fn text(body: &str) -> Payload {
+        Payload::Text {
+            body: String::from(body),
+            sent_at_ms: 1_700_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let envelope = seal(&ALICE, &public_key(&BOB), "m1", &text("hi"), &[7; 44]).unwrap();
+        assert_eq!(envelope.from, bytes_to_hex(&public_key(&ALICE)));
+        assert!(!envelope.ciphertext.contains(&bytes_to_hex(b"hi")));
+
+        let (from, payload) = open(&BOB, &envelope).unwrap();
+        assert_eq!(from, public_key(&ALICE));
+        assert_eq!(payload, text("hi"));
+
+        // Only the recipient can open it
+        assert!(open(&ALICE, &envelope).is_err());
+        assert!(open(&[3; 32], &envelope).is_err());
+    }
+
+    #[test]
+    fn test_tampering_detected() {
+        let envelope = seal(&ALICE, &public_key(&BOB), "m1", &text("hi"), &[7; 44]).unwrap();
+
+        let mut relabelled = envelope.clone();
+        relabelled.id = String::from("m2");
+        assert!(open(&BOB, &relabelled).is_err());
+
+        // Claiming another sender changes the key, so authentication fails
+        let mut forged = envelope.clone();
+        forged.from = bytes_to_hex(&public_key(&[3; 32]));
+        assert!(open(&BOB, &forged).is_err());
+
+        let mut flipped = envelope;
+        let last = flipped.ciphertext.pop().unwrap();
+        flipped.ciphertext.push(if last == '0' { '1' } else { '0' });
+        assert!(open(&BOB, &flipped).is_err());
+    }
+
+    #[test]
+    fn test_low_order_recipient_refused() {
+        assert!(seal(&ALICE, &[0; 32], "m1", &text("hi"), &[7; 44]).is_err());
+    }
+}
@@ -0,0 +1,1765 @@
+//! Messaging Service
+//!
+//! The MessagingService exchanges end-to-end encrypted messages between a
+//! user's enrolled machines and with their address-book contacts. It:
+//! - Reads this machine's encryption key and the user's other machines'
+//!   keys from KeystoreService
+//! - Seals each message separately for every key it goes to (see [`envelope`])
+//! - Posts envelopes to relay mailboxes and reads its own through
+//!   NetworkService
+//! - Queues envelopes in VFS while offline and retries them (see [`store`])
+//! - Sends delivery and read receipts, and applies those it gets back
+//! - Shows new messages as desktop notifications and publishes them on the
+//!   event bus
+//! - Carries ClipboardService's entries between the user's machines
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - OPEN: Machine keys read from the keystore AND message store loaded
+//! - SEND: Message sealed for every recipient key AND recorded with its
+//!   envelopes queued AND the store marked for writing
+//! - LIST: Messages read from the store
+//! - MARK_READ: Messages marked read AND read receipts queued
+//! - SYNC: Outbox due for retry AND a mailbox read started
+//! - SEND_CLIPBOARD: Entry sealed for every other machine AND its envelopes
+//!   queued AND the store marked for writing
+//!
+//! **Acceptable partial failure:**
+//! - Relay unreachable → envelopes stay queued and are retried with backoff
+//!   until [`store::OUTBOX_TTL_MS`], then the message is marked failed
+//! - Message store write fails → changes stay in memory until the next write
+//! - Receipt not sent (outbox full) → the sender sees the message as sent
+//! - Clipboard entry not sent (outbox half full) → the copy stays local
+//!
+//! **Forbidden:**
+//! - Posting anything but sealed envelopes to a relay
+//! - Accepting a message from a key that is neither one of the user's
+//!   machines nor a contact's
+//! - Accepting a receipt from anyone but the peer the message went to
+//! - Sealing clipboard entries for anyone but the ClipboardService, or
+//!   accepting them from anyone but the user's machines
+//! - Changing messages before the store has loaded (the write would replace it)
+//! - Unbounded message, outbox or pending request growth (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_MESSAGING_OPEN (0x8900)`: Open a user's mailbox on this machine
+//! - `MSG_MESSAGING_SEND (0x8902)`: Send a message to a contact or the user's machines
+//! - `MSG_MESSAGING_LIST (0x8904)`: List messages, newest first
+//! - `MSG_MESSAGING_MARK_READ (0x8906)`: Mark incoming messages read
+//! - `MSG_MESSAGING_SYNC (0x8908)`: Retry the outbox and read mailboxes now
+//! - `MSG_MESSAGING_SEND_CLIPBOARD (0x890A)`: Send a clipboard entry to the
+//!   user's machines (ClipboardService only)
+//!
+//! # Clipboard Entries
+//!
+//! Clipboard entries travel as [`Payload::Clipboard`] envelopes, sealed
+//! like messages but kept out of the message history: they get no receipt
+//! and no notification, and a received one is handed straight to the
+//! ClipboardService as `MSG_CLIPBOARD_REMOTE`. Which entries are sent, and
+//! whether received ones are applied, is up to that service.
+//!
+//! # Relays
+//!
+//! A relay keeps a mailbox per encryption key: envelopes are POSTed to
+//! `https://{relay}/v1/mailbox/{key}`, and the owner GETs
+//! `.../v1/mailbox/{key}?after={cursor}` for `{"envelopes": [...], "cursor"}`.
+//! The user's own machines share the relays given to OPEN; contacts are
+//! reached through the relay endpoints in their cards. One relay request is
+//! in flight at a time.
+//!
+//! # Keys and Contacts
+//!
+//! Keys are read from KeystoreService by name, one request at a time, as
+//! its responses carry no request ID. Contacts come from `MSG_CONTACT_LIST`
+//! sent to the AddressBookService. The supervisor routes both services'
+//! replies back here via Init. Contacts are refreshed on open and on every
+//! mailbox poll.
+//!
+//! # Storage Access
+//!
+//! This service uses VFS IPC (async/await pattern, see
+//! [`zos_vfs::client::futures`]) to persist messages and the outbox. All
+//! storage operations flow through the VFS service per Invariant 31.
+
+extern crate alloc;
+
+pub mod envelope;
+pub mod store;
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+use zos_identity::fingerprint::key_fingerprint;
+use zos_identity::keystore::{LocalKeyStore, MachineKeyRecord};
+use zos_identity::serde_helpers::u128_hex_string;
+use zos_ipc::{clipboard, contacts, debug, events, keystore_svc, net};
+use zos_network::{HttpRequest, HttpResponse};
+use zos_vfs::client::futures::VfsExecutor;
+use zos_vfs::client::keystore_async::{
+    parse_list_response, parse_read_response, KeystoreListRequest, KeystoreReadRequest,
+};
+
+use crate::manifests::MESSAGING_MANIFEST;
+use crate::response::JsonResponder;
+use crate::services::contacts::store::{parse_key, Contact, EndpointKind};
+use crate::services::identity::utils::bytes_to_hex;
+use crate::services::time::alarms::Notification;
+use crate::trust::CallerNames;
+use envelope::{Envelope, Payload, ReceiptStatus, SEAL_RANDOM_BYTES};
+
+pub use store::{
+    store_path, Direction, MessageRecord, MessageStatus, MessageStore, OutboxEntry, Peer,
+    MAX_BODY_LEN, MAX_MESSAGES,
+};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for messaging service - re-exported from zos-ipc.
+pub mod messaging_msg {
+    pub use zos_ipc::messaging::*;
+}
+
+/// Mailbox loads and saves in flight at once (Rule 11)
+const MAX_VFS_TASKS: usize = 8;
+
+/// Maximum sends waiting on the contact list (DoS protection per Rule 11)
+const MAX_PARKED_SENDS: usize = 16;
+
+/// Maximum IDs in one MSG_MESSAGING_MARK_READ (DoS protection per Rule 11)
+const MAX_MARK_READ: usize = 256;
+
+/// Maximum envelopes handled from one mailbox page
+const MAX_PAGE_ENVELOPES: usize = 100;
+
+/// Messages returned by MSG_MESSAGING_LIST without a limit
+const DEFAULT_LIST_LIMIT: usize = 100;
+
+/// How often mailboxes are read
+const POLL_INTERVAL_MS: u64 = 30_000;
+
+/// Timeout given to NetworkService for each relay request
+const RELAY_TIMEOUT_MS: u32 = 15_000;
+
+/// How long a relay request may go unanswered before it is given up on
+const NET_TIMEOUT_MS: u64 = 60_000;
+
+/// Characters of a message shown in its notification
+const PREVIEW_CHARS: usize = 120;
+
+// =============================================================================
+// Request Types
+// =============================================================================
+
+/// Payload of MSG_MESSAGING_OPEN.
+#[derive(Clone, Debug, Deserialize)]
+struct OpenRequest {
+    #[serde(with = "u128_hex_string")]
+    user_id: u128,
+    #[serde(default)]
+    relays: Option<Vec<String>>,
+}
+
+/// Payload of MSG_MESSAGING_OPEN_RESPONSE.
+#[derive(Clone, Debug, Serialize)]
+struct OpenResponse {
+    /// This machine's encryption key, hex
+    key: String,
+    fingerprint: String,
+    /// The user's other enrolled machines
+    machines: u32,
+    relays: Vec<String>,
+}
+
+/// Payload of MSG_MESSAGING_SEND.
+#[derive(Clone, Debug, Deserialize)]
+struct SendRequest {
+    to: Peer,
+    body: String,
+}
+
+/// Payload of MSG_MESSAGING_SEND_CLIPBOARD, and of the MSG_CLIPBOARD_REMOTE
+/// it becomes on the other machines.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ClipboardEntry {
+    text: String,
+    copied_at_ms: u64,
+}
+
+/// Payload of MSG_MESSAGING_LIST.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct ListRequest {
+    #[serde(default)]
+    peer: Option<Peer>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Payload of MSG_MESSAGING_LIST_RESPONSE.
+#[derive(Clone, Debug, Serialize)]
+struct ListResponse {
+    messages: Vec<MessageRecord>,
+}
+
+/// Payload of MSG_MESSAGING_MARK_READ.
+#[derive(Clone, Debug, Deserialize)]
+struct MarkReadRequest {
+    ids: Vec<String>,
+}
+
+/// Payload of MSG_CONTACT_LIST_RESPONSE, as far as it is needed here.
+#[derive(Clone, Debug, Deserialize)]
+struct ContactList {
+    contacts: Vec<Contact>,
+}
+
+/// A page of a relay mailbox.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct MailboxPage {
+    #[serde(default)]
+    envelopes: Vec<Envelope>,
+    #[serde(default)]
+    cursor: Option<String>,
+}
+
+// =============================================================================
+// State
+// =============================================================================
+
+/// The mailbox open on this machine.
+struct Account {
+    user_id: u128,
+    /// This machine's encryption secret; zeroed on drop
+    secret: [u8; 32],
+    /// Its public key, hex: the mailbox this machine reads
+    key: String,
+    /// The user's other machines' encryption keys
+    machines: Vec<[u8; 32]>,
+}
+
+impl Drop for Account {
+    fn drop(&mut self) {
+        self.secret.fill(0);
+    }
+}
+
+/// What messaging needs of a contact.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ContactRoute {
+    name: String,
+    key: Option<[u8; 32]>,
+    relays: Vec<String>,
+}
+
+impl ContactRoute {
+    fn from_contact(contact: &Contact) -> Self {
+        Self {
+            name: contact.fields.name.clone(),
+            key: contact.fields.encryption_key.as_deref().and_then(parse_key),
+            relays: contact
+                .fields
+                .endpoints
+                .iter()
+                .filter(|e| e.kind == EndpointKind::Relay)
+                .map(|e| e.address.clone())
+                .filter(|relay| store::validate_relay(relay).is_ok())
+                .collect(),
+        }
+    }
+}
+
+/// Keystore reads of an OPEN, one at a time.
+enum KeyLoad {
+    /// Reading the user's public keys for this machine's encryption key
+    PublicKeys,
+    /// Listing the user's machine key records
+    Listing { key: [u8; 32] },
+    /// Reading the machine key records in turn
+    Machines {
+        key: [u8; 32],
+        remaining: Vec<String>,
+        secret: Option<[u8; 32]>,
+        others: Vec<[u8; 32]>,
+    },
+}
+
+/// An OPEN waiting on the keystore.
+struct Opening {
+    client_pid: u32,
+    cap_slots: Vec<u32>,
+    user_id: u128,
+    relays: Option<Vec<String>>,
+    load: KeyLoad,
+}
+
+/// A send waiting on the contact list.
+struct ParkedSend {
+    client_pid: u32,
+    cap_slots: Vec<u32>,
+    request: SendRequest,
+}
+
+/// The relay request in flight.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum NetOp {
+    /// Posting an envelope
+    Post { id: String, to: String },
+    /// Reading this machine's mailbox
+    Fetch { relay: String },
+}
+
+/// A finished VFS task.
+enum Done {
+    /// The message store read on OPEN
+    Loaded {
+        client_pid: u32,
+        cap_slots: Vec<u32>,
+        relays: Option<Vec<String>>,
+        result: Result<Vec<u8>, String>,
+    },
+    /// A write of the message store after a change
+    Saved(Result<(), String>),
+}
+
+// =============================================================================
+// MessagingService Application
+// =============================================================================
+
+/// MessagingService - end-to-end encrypted messages via relays
+pub struct MessagingService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// The open mailbox, if any
+    account: Option<Account>,
+    /// An OPEN reading keys, if any
+    opening: Option<Opening>,
+    /// The open user's messages, outbox and relays
+    store: MessageStore,
+    /// Whether the message store has been loaded; until then it is neither
+    /// changed nor saved
+    loaded: bool,
+    /// Whether the store has changes not yet written
+    dirty: bool,
+    /// Contacts by ID, from the AddressBookService
+    contacts: BTreeMap<u32, ContactRoute>,
+    /// Whether a contact list request is outstanding
+    contacts_requested: bool,
+    /// Sends to contacts not in `contacts` yet, oldest first
+    parked: VecDeque<ParkedSend>,
+    /// The relay request in flight, with when it started
+    net: Option<(NetOp, u64)>,
+    /// When mailboxes are next read
+    next_poll_ms: u64,
+    /// Relay the next mailbox read goes to
+    next_relay: usize,
+    /// VFS reads and writes in progress
+    vfs: VfsExecutor<Done>,
+    /// Writes of the message store in progress
+    saving: usize,
+    /// Names callers run under, for trusting the ClipboardService
+    names: CallerNames,
+}
+
+impl Default for MessagingService {
+    fn default() -> Self {
+        Self {
+            registered: false,
+            account: None,
+            opening: None,
+            store: MessageStore::default(),
+            loaded: false,
+            dirty: false,
+            contacts: BTreeMap::new(),
+            contacts_requested: false,
+            parked: VecDeque::new(),
+            net: None,
+            next_poll_ms: 0,
+            next_relay: 0,
+            vfs: VfsExecutor::new(),
+            saving: 0,
+            names: CallerNames::default(),
+        }
+    }
+}
+
+impl MessagingService {
+    /// Whether another mailbox load or save may start.
+    fn check_task_limit(&self) -> bool {
+        if self.vfs.running() >= MAX_VFS_TASKS {
+            syscall::debug(&format!(
+                "MessagingService: VFS task limit reached ({}/{})",
+                self.vfs.running(),
+                MAX_VFS_TASKS
+            ));
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Start a write of the message store to `user_id`'s mailbox.
+    fn start_save(&mut self, user_id: u128) {
+        let value = self.store.to_json();
+        let path = store_path(user_id);
+        let vfs = self.vfs.handle();
+        self.saving += 1;
+        self.vfs
+            .spawn(async move { Done::Saved(vfs.write(&path, &value).await) });
+        // Fails right away if the VFS can't be reached
+        self.finish_tasks();
+    }
+
+    /// Write the message store if it changed. One write is in flight at a
+    /// time; later changes go out with the next one.
+    fn save_store(&mut self) {
+        let Some(user_id) = self.account.as_ref().map(|a| a.user_id) else {
+            return;
+        };
+        if !self.loaded || !self.dirty || self.saving > 0 || !self.check_task_limit() {
+            return;
+        }
+        self.dirty = false;
+        self.start_save(user_id);
+    }
+
+    // =========================================================================
+    // Keys
+    // =========================================================================
+
+    /// Ask KeystoreService for the next key of the OPEN in progress.
+    fn request_keys(&mut self) -> Result<(), AppError> {
+        let Some(opening) = &self.opening else {
+            return Ok(());
+        };
+        let sent = match &opening.load {
+            KeyLoad::PublicKeys => {
+                let request = KeystoreReadRequest {
+                    key: LocalKeyStore::storage_path(opening.user_id),
+                };
+                let json = serde_json::to_vec(&request).unwrap_or_default();
+                syscall::send_named("keystore", keystore_svc::MSG_KEYSTORE_READ, &json)
+            }
+            KeyLoad::Listing { .. } => {
+                let request = KeystoreListRequest {
+                    prefix: format!("/keys/{}/identity/machine/", opening.user_id),
+                };
+                let json = serde_json::to_vec(&request).unwrap_or_default();
+                syscall::send_named("keystore", keystore_svc::MSG_KEYSTORE_LIST, &json)
+            }
+            KeyLoad::Machines { remaining, .. } => match remaining.last() {
+                Some(path) => {
+                    let request = KeystoreReadRequest { key: path.clone() };
+                    let json = serde_json::to_vec(&request).unwrap_or_default();
+                    syscall::send_named("keystore", keystore_svc::MSG_KEYSTORE_READ, &json)
+                }
+                None => return self.keys_loaded(),
+            },
+        };
+        if let Err(e) = sent {
+            return self.open_failed(&format!("KeystoreService unreachable: {}", e));
+        }
+        Ok(())
+    }
+
+    /// Handle a keystore read or list response for the OPEN in progress.
+    fn handle_keystore_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Some(opening) = &mut self.opening else {
+            syscall::debug("MessagingService: Keystore response but no open in progress");
+            return Ok(());
+        };
+        let listing = msg.tag == keystore_svc::MSG_KEYSTORE_LIST_RESPONSE;
+        match (&mut opening.load, listing) {
+            (KeyLoad::PublicKeys, false) => {
+                let keys = parse_read_response(&msg.data)
+                    .ok()
+                    .and_then(|data| serde_json::from_slice::<LocalKeyStore>(&data).ok());
+                let Some(keys) = keys else {
+                    return self.open_failed("No machine keys for user on this machine");
+                };
+                opening.load = KeyLoad::Listing {
+                    key: keys.machine_encryption_public_key,
+                };
+            }
+            (KeyLoad::Listing { key }, true) => {
+                let remaining = match parse_list_response(&msg.data) {
+                    Ok(paths) => paths,
+                    Err(e) => return self.open_failed(&format!("Keystore list failed: {}", e)),
+                };
+                opening.load = KeyLoad::Machines {
+                    key: *key,
+                    remaining,
+                    secret: None,
+                    others: Vec::new(),
+                };
+            }
+            (
+                KeyLoad::Machines {
+                    key,
+                    remaining,
+                    secret,
+                    others,
+                },
+                false,
+            ) => {
+                let path = remaining.pop().unwrap_or_default();
+                let record = parse_read_response(&msg.data)
+                    .ok()
+                    .and_then(|data| serde_json::from_slice::<MachineKeyRecord>(&data).ok());
+                match record {
+                    Some(record) if record.encryption_public_key == *key => {
+                        // Take the secret only if it is the one for this key
+                        if let Some(sk) = record.encryption_sk {
+                            if envelope::public_key(&sk) == *key {
+                                *secret = Some(sk);
+                            }
+                        }
+                    }
+                    Some(record) => {
+                        if !others.contains(&record.encryption_public_key) {
+                            others.push(record.encryption_public_key);
+                        }
+                    }
+                    None => syscall::debug(&format!(
+                        "MessagingService: Skipping unreadable machine record {}",
+                        path
+                    )),
+                }
+            }
+            _ => {
+                syscall::debug(&format!(
+                    "MessagingService: Unexpected keystore response 0x{:x}",
+                    msg.tag
+                ));
+                return Ok(());
+            }
+        }
+        self.request_keys()
+    }
+
+    /// All machine records read: open the account and load its messages.
+    fn keys_loaded(&mut self) -> Result<(), AppError> {
+        let Some(mut opening) = self.opening.take() else {
+            return Ok(());
+        };
+        let KeyLoad::Machines {
+            key,
+            secret,
+            others,
+            ..
+        } = &mut opening.load
+        else {
+            return Ok(());
+        };
+        let Some(secret) = secret.take() else {
+            self.opening = Some(opening);
+            return self.open_failed("This machine holds no encryption key for the user");
+        };
+        self.account = Some(Account {
+            user_id: opening.user_id,
+            secret,
+            key: bytes_to_hex(key),
+            machines: core::mem::take(others),
+        });
+        let path = store_path(opening.user_id);
+        let client_pid = opening.client_pid;
+        let cap_slots = core::mem::take(&mut opening.cap_slots);
+        let relays = opening.relays.take();
+        let vfs = self.vfs.handle();
+        self.vfs.spawn(async move {
+            let result = vfs.read(&path).await;
+            Done::Loaded {
+                client_pid,
+                cap_slots,
+                relays,
+                result,
+            }
+        });
+        self.finish_tasks();
+        Ok(())
+    }
+
+    /// Give up the OPEN in progress.
+    fn open_failed(&mut self, error: &str) -> Result<(), AppError> {
+        let Some(opening) = self.opening.take() else {
+            return Ok(());
+        };
+        syscall::debug(&format!("MessagingService: Open failed: {}", error));
+        self.send_error_response(
+            opening.client_pid,
+            &opening.cap_slots,
+            messaging_msg::MSG_MESSAGING_OPEN_RESPONSE,
+            error,
+        )
+    }
+
+    // =========================================================================
+    // Contacts
+    // =========================================================================
+
+    /// Ask the AddressBookService for the contact list, unless already asked.
+    fn refresh_contacts(&mut self) {
+        if self.contacts_requested {
+            return;
+        }
+        match syscall::send_named("contacts", contacts::MSG_CONTACT_LIST, b"{}") {
+            Ok(()) => self.contacts_requested = true,
+            Err(e) => syscall::debug(&format!(
+                "MessagingService: AddressBookService unreachable: {}",
+                e
+            )),
+        }
+    }
+
+    /// Handle MSG_CONTACT_LIST_RESPONSE: refresh the cache and retry parked
+    /// sends.
+    fn handle_contact_list(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        self.contacts_requested = false;
+        match serde_json::from_slice::<ContactList>(&msg.data) {
+            Ok(list) => {
+                self.contacts = list
+                    .contacts
+                    .iter()
+                    .map(|c| (c.id, ContactRoute::from_contact(c)))
+                    .collect();
+            }
+            Err(_) => syscall::debug(&format!(
+                "MessagingService: Contact list refused: {}",
+                String::from_utf8_lossy(&msg.data)
+            )),
+        }
+        while let Some(parked) = self.parked.pop_front() {
+            let result = self.send_message(parked.request, now_ms);
+            self.send_record_response(parked.client_pid, &parked.cap_slots, result)?;
+        }
+        Ok(())
+    }
+
+    /// Peer a key belongs to: one of the user's machines or a contact.
+    fn peer_for(&self, key: &[u8; 32]) -> Option<Peer> {
+        let account = self.account.as_ref()?;
+        if account.machines.contains(key) {
+            return Some(Peer::Machines);
+        }
+        self.contacts
+            .iter()
+            .find(|(_, route)| route.key.as_ref() == Some(key))
+            .map(|(id, _)| Peer::Contact(*id))
+    }
+
+    /// Relays a peer reads.
+    fn relays_for(&self, peer: &Peer) -> Vec<String> {
+        match peer {
+            Peer::Machines => self.store.relays().to_vec(),
+            Peer::Contact(id) => self
+                .contacts
+                .get(id)
+                .map(|route| route.relays.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    // =========================================================================
+    // Sealing
+    // =========================================================================
+
+    /// Seal a payload for `to` with this machine's key.
+    fn seal_for(&self, to: &[u8; 32], id: &str, payload: &Payload) -> Result<Envelope, String> {
+        let account = self
+            .account
+            .as_ref()
+            .ok_or_else(|| String::from("No mailbox open"))?;
+        let mut random = [0u8; SEAL_RANDOM_BYTES];
+        getrandom::getrandom(&mut random)
+            .map_err(|e| format!("Random number generation failed: {}", e))?;
+        let envelope = envelope::seal(&account.secret, to, id, payload, &random);
+        random.fill(0);
+        envelope
+    }
+
+    /// Seal `payload` for each recipient and queue the envelopes as a message.
+    fn send_message(&mut self, request: SendRequest, now_ms: u64) -> Result<MessageRecord, String> {
+        let account = self
+            .account
+            .as_ref()
+            .ok_or_else(|| String::from("No mailbox open"))?;
+        let recipients: Vec<[u8; 32]> = match &request.to {
+            Peer::Machines => {
+                if account.machines.is_empty() {
+                    return Err(String::from("No other machines enrolled"));
+                }
+                account.machines.clone()
+            }
+            Peer::Contact(id) => {
+                let route = self
+                    .contacts
+                    .get(id)
+                    .ok_or_else(|| format!("Contact {} not found", id))?;
+                vec![route
+                    .key
+                    .ok_or_else(|| format!("Contact {} has no encryption key", id))?]
+            }
+        };
+        let relays = self.relays_for(&request.to);
+        if relays.is_empty() {
+            return Err(match request.to {
+                Peer::Machines => String::from("No relays configured"),
+                Peer::Contact(id) => format!("Contact {} has no relay endpoint", id),
+            });
+        }
+
+        let id = new_message_id()?;
+        let payload = Payload::Text {
+            body: request.body.clone(),
+            sent_at_ms: now_ms,
+        };
+        let mut entries = Vec::with_capacity(recipients.len());
+        for key in &recipients {
+            entries.push(OutboxEntry {
+                envelope: self.seal_for(key, &id, &payload)?,
+                relays: relays.clone(),
+                message: Some(id.clone()),
+                queued_at_ms: now_ms,
+                attempts: 0,
+                next_attempt_ms: 0,
+            });
+        }
+        let record = MessageRecord {
+            id,
+            peer: request.to,
+            direction: Direction::Outgoing,
+            body: request.body,
+            sent_at_ms: now_ms,
+            status: MessageStatus::Queued,
+            updated_at_ms: now_ms,
+            sender_key: None,
+        };
+        self.store.send(record.clone(), entries)?;
+        self.dirty = true;
+        Ok(record)
+    }
+
+    /// Queue a receipt for message `of` back to the key it came from.
+    fn queue_receipt(
+        &mut self,
+        peer: &Peer,
+        to: &[u8; 32],
+        of: &str,
+        status: ReceiptStatus,
+        now_ms: u64,
+    ) {
+        let relays = self.relays_for(peer);
+        if relays.is_empty() {
+            return;
+        }
+        let payload = Payload::Receipt {
+            of: String::from(of),
+            status,
+        };
+        let envelope = match new_message_id().and_then(|id| self.seal_for(to, &id, &payload)) {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                syscall::debug(&format!(
+                    "MessagingService: Receipt for {} not sealed: {}",
+                    of, e
+                ));
+                return;
+            }
+        };
+        let entry = OutboxEntry {
+            envelope,
+            relays,
+            message: None,
+            queued_at_ms: now_ms,
+            attempts: 0,
+            next_attempt_ms: 0,
+        };
+        if self.store.queue_receipt(entry) {
+            self.dirty = true;
+        }
+    }
+
+    /// Seal a clipboard entry for each of the user's other machines and
+    /// queue it. Returns the number of machines.
+    fn send_clipboard(&mut self, entry: ClipboardEntry, now_ms: u64) -> Result<u32, String> {
+        let account = self
+            .account
+            .as_ref()
+            .ok_or_else(|| String::from("No mailbox open"))?;
+        if account.machines.is_empty() {
+            return Err(String::from("No other machines enrolled"));
+        }
+        let machines = account.machines.clone();
+        let relays = self.relays_for(&Peer::Machines);
+        if relays.is_empty() {
+            return Err(String::from("No relays configured"));
+        }
+
+        let id = new_message_id()?;
+        let payload = Payload::Clipboard {
+            text: entry.text,
+            copied_at_ms: entry.copied_at_ms,
+        };
+        let mut entries = Vec::with_capacity(machines.len());
+        for key in &machines {
+            entries.push(OutboxEntry {
+                envelope: self.seal_for(key, &id, &payload)?,
+                relays: relays.clone(),
+                message: None,
+                queued_at_ms: now_ms,
+                attempts: 0,
+                next_attempt_ms: 0,
+            });
+        }
+        if !self.store.queue_clipboard(entries) {
+            return Err(format!(
+                "Outbox too full for clipboard entries ({} envelopes waiting)",
+                self.store.outbox_len()
+            ));
+        }
+        self.dirty = true;
+        Ok(machines.len() as u32)
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Refuse requests until a mailbox is open and loaded. Returns true if
+    /// refused.
+    fn refuse_until_open(&self, msg: &Message, tag: u32) -> Result<bool, AppError> {
+        let error = if self.account.is_none() {
+            "No mailbox open"
+        } else if !self.loaded {
+            "Service busy: messages are loading"
+        } else {
+            return Ok(false);
+        };
+        self.send_error_response(msg.from_pid, &msg.cap_slots, tag, error)?;
+        Ok(true)
+    }
+
+    /// Handle MSG_MESSAGING_OPEN
+    fn handle_open(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = messaging_msg::MSG_MESSAGING_OPEN_RESPONSE;
+        let request: OpenRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid open: expected {\"user_id\": hex, \"relays\": [string]?}",
+                );
+            }
+        };
+        if self.opening.is_some() || (self.account.is_some() && !self.loaded) {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Service busy: a mailbox is opening",
+            );
+        }
+        if let Some(relays) = &request.relays {
+            let checked = MessageStore::default().set_relays(relays.clone());
+            if let Err(e) = checked {
+                return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e);
+            }
+        }
+        if !self.check_task_limit() {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Service busy: VFS task limit reached",
+            );
+        }
+
+        // Write out the mailbox being closed before its store is replaced
+        if self.dirty {
+            if let Some(user_id) = self.account.as_ref().map(|a| a.user_id) {
+                self.start_save(user_id);
+            }
+        }
+        self.account = None;
+        self.store = MessageStore::default();
+        self.loaded = false;
+        self.dirty = false;
+        self.net = None;
+
+        syscall::debug(&format!(
+            "MessagingService: PID {} opens mailbox of user {:032x}",
+            msg.from_pid, request.user_id
+        ));
+        self.opening = Some(Opening {
+            client_pid: msg.from_pid,
+            cap_slots: msg.cap_slots.clone(),
+            user_id: request.user_id,
+            relays: request.relays,
+            load: KeyLoad::PublicKeys,
+        });
+        self.request_keys()
+    }
+
+    /// Handle MSG_MESSAGING_SEND
+    fn handle_send(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let tag = messaging_msg::MSG_MESSAGING_SEND_RESPONSE;
+        if self.refuse_until_open(msg, tag)? {
+            return Ok(());
+        }
+        let request: SendRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid send: expected {\"to\": {\"contact\": u32} | \"machines\", \"body\": string}",
+                );
+            }
+        };
+        if request.body.is_empty() || request.body.len() > MAX_BODY_LEN {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                &format!("Message body must be 1 to {} bytes", MAX_BODY_LEN),
+            );
+        }
+
+        // A contact added since the last refresh: wait for the list
+        if let Peer::Contact(id) = request.to {
+            if !self.contacts.contains_key(&id) {
+                if self.parked.len() >= MAX_PARKED_SENDS {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Service busy: too many sends waiting on contacts",
+                    );
+                }
+                self.parked.push_back(ParkedSend {
+                    client_pid: msg.from_pid,
+                    cap_slots: msg.cap_slots.clone(),
+                    request,
+                });
+                self.refresh_contacts();
+                return Ok(());
+            }
+        }
+
+        let result = self.send_message(request, now_ms);
+        self.send_record_response(msg.from_pid, &msg.cap_slots, result)
+    }
+
+    /// Handle MSG_MESSAGING_LIST
+    fn handle_list(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = messaging_msg::MSG_MESSAGING_LIST_RESPONSE;
+        if self.refuse_until_open(msg, tag)? {
+            return Ok(());
+        }
+        let request: ListRequest = if msg.data.is_empty() {
+            ListRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid list: expected {\"peer\": {\"contact\": u32} | \"machines\"?, \"limit\": u32?}",
+                    );
+                }
+            }
+        };
+        let limit = request
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .min(MAX_MESSAGES);
+        let messages = self.store.list(request.peer.as_ref(), limit);
+        let json = serde_json::to_vec(&ListResponse { messages }).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_MESSAGING_MARK_READ
+    fn handle_mark_read(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let tag = messaging_msg::MSG_MESSAGING_MARK_READ_RESPONSE;
+        if self.refuse_until_open(msg, tag)? {
+            return Ok(());
+        }
+        let request = match serde_json::from_slice::<MarkReadRequest>(&msg.data) {
+            Ok(r) if r.ids.len() <= MAX_MARK_READ => r,
+            _ => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    &format!(
+                        "Invalid mark read: expected {{\"ids\": [string]}}, at most {}",
+                        MAX_MARK_READ
+                    ),
+                );
+            }
+        };
+
+        let marked = self.store.mark_read(&request.ids, now_ms);
+        for record in &marked {
+            if let Some(key) = record.sender_key.as_deref().and_then(parse_key) {
+                self.queue_receipt(&record.peer, &key, &record.id, ReceiptStatus::Read, now_ms);
+            }
+        }
+        if !marked.is_empty() {
+            self.dirty = true;
+        }
+        let json =
+            serde_json::to_vec(&serde_json::json!({ "marked": marked.len() })).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_MESSAGING_SYNC
+    fn handle_sync(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = messaging_msg::MSG_MESSAGING_SYNC_RESPONSE;
+        if self.refuse_until_open(msg, tag)? {
+            return Ok(());
+        }
+        self.store.retry_now();
+        self.next_poll_ms = 0;
+        self.refresh_contacts();
+        let json = serde_json::to_vec(&serde_json::json!({ "queued": self.store.outbox_len() }))
+            .unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_MESSAGING_SEND_CLIPBOARD
+    fn handle_send_clipboard(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let tag = messaging_msg::MSG_MESSAGING_SEND_CLIPBOARD_RESPONSE;
+        // Rule 4: only the clipboard's owner decides what leaves this machine
+        if !self.names.is(msg.from_pid, "clipboard") {
+            syscall::debug(&format!(
+                "MessagingService: SECURITY - SEND_CLIPBOARD denied for PID {}",
+                msg.from_pid
+            ));
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: SEND_CLIPBOARD is for the clipboard service",
+            );
+        }
+        if self.refuse_until_open(msg, tag)? {
+            return Ok(());
+        }
+        let entry: ClipboardEntry = match serde_json::from_slice(&msg.data) {
+            Ok(e) => e,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid clipboard entry: expected {\"text\": string, \"copied_at_ms\": u64}",
+                );
+            }
+        };
+        if entry.text.is_empty() || entry.text.len() > MAX_BODY_LEN {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                &format!("Clipboard entry must be 1 to {} bytes", MAX_BODY_LEN),
+            );
+        }
+
+        match self.send_clipboard(entry, now_ms) {
+            Ok(machines) => {
+                let json = serde_json::to_vec(&serde_json::json!({ "machines": machines }))
+                    .unwrap_or_default();
+                self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    // =========================================================================
+    // Transport
+    // =========================================================================
+
+    /// Save changes, post due envelopes and read mailboxes.
+    fn tick(&mut self, now_ms: u64) {
+        if self.account.is_none() || !self.loaded {
+            return;
+        }
+        self.save_store();
+
+        if let Some((op, started_ms)) = &self.net {
+            if now_ms.saturating_sub(*started_ms) < NET_TIMEOUT_MS {
+                return;
+            }
+            syscall::debug("MessagingService: Relay request timed out");
+            if let NetOp::Post { id, to } = op.clone() {
+                self.post_failed(&id, &to, now_ms);
+            }
+            self.net = None;
+        }
+
+        if let Some(entry) = self.store.next_due(now_ms).cloned() {
+            self.start_post(entry, now_ms);
+        } else if now_ms >= self.next_poll_ms {
+            self.next_poll_ms = now_ms + POLL_INTERVAL_MS;
+            self.refresh_contacts();
+            self.start_fetch(now_ms);
+        }
+    }
+
+    /// Send a request to NetworkService as relay operation `op`.
+    fn start_net(&mut self, request: HttpRequest, op: NetOp, now_ms: u64) -> Result<(), String> {
+        let json = serde_json::to_vec(&request.with_timeout(RELAY_TIMEOUT_MS))
+            .map_err(|e| format!("Request serialization failed: {}", e))?;
+        syscall::send_named("network", net::MSG_NET_REQUEST, &json)
+            .map_err(|e| format!("NetworkService unreachable: {}", e))?;
+        self.net = Some((op, now_ms));
+        Ok(())
+    }
+
+    /// Post an outbox envelope to its next relay.
+    fn start_post(&mut self, entry: OutboxEntry, now_ms: u64) {
+        let (id, to) = (entry.envelope.id.clone(), entry.envelope.to.clone());
+        let Some(relay) = entry.relay() else {
+            self.post_failed(&id, &to, now_ms);
+            return;
+        };
+        let url = store::mailbox_url(relay, &to);
+        let body = serde_json::to_vec(&entry.envelope).unwrap_or_default();
+        let request = HttpRequest::post(url).with_json_body(body);
+        if let Err(e) = self.start_net(
+            request,
+            NetOp::Post {
+                id: id.clone(),
+                to: to.clone(),
+            },
+            now_ms,
+        ) {
+            syscall::debug(&format!("MessagingService: Post of {} failed: {}", id, e));
+            self.post_failed(&id, &to, now_ms);
+        }
+    }
+
+    /// Read this machine's mailbox on the next relay in turn.
+    fn start_fetch(&mut self, now_ms: u64) {
+        let Some(account) = &self.account else {
+            return;
+        };
+        let relays = self.store.relays();
+        if relays.is_empty() {
+            return;
+        }
+        let relay = relays[self.next_relay % relays.len()].clone();
+        self.next_relay = self.next_relay.wrapping_add(1);
+        let mut url = store::mailbox_url(&relay, &account.key);
+        if let Some(cursor) = self.store.cursor(&relay) {
+            url.push_str("?after=");
+            url.push_str(cursor);
+        }
+        if let Err(e) = self.start_net(HttpRequest::get(url), NetOp::Fetch { relay }, now_ms) {
+            syscall::debug(&format!("MessagingService: Mailbox read failed: {}", e));
+        }
+    }
+
+    /// Record a failed post; the store retries it or gives the message up.
+    fn post_failed(&mut self, id: &str, to: &str, now_ms: u64) {
+        if let Some(record) = self.store.post_failed(id, to, now_ms) {
+            syscall::debug(&format!(
+                "MessagingService: Message {} failed: relays unreachable",
+                record.id
+            ));
+            self.publish_status(&record);
+        }
+        self.dirty = true;
+    }
+
+    /// Handle MSG_NET_RESPONSE for the relay request in flight.
+    fn handle_net_response(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let Some((op, _)) = self.net.take() else {
+            syscall::debug("MessagingService: Network response but no relay request in flight");
+            return Ok(());
+        };
+        // Format: [request_id: u32, response: HttpResponse JSON]
+        let response = msg
+            .data
+            .get(4..)
+            .and_then(|json| serde_json::from_slice::<HttpResponse>(json).ok());
+        let body = match response {
+            Some(response) if response.is_success() => response.result.ok().map(|r| r.body),
+            _ => None,
+        };
+
+        match op {
+            NetOp::Post { id, to } => match body {
+                Some(_) => {
+                    if let Some(record) = self.store.posted(&id, &to, now_ms) {
+                        self.publish_status(&record);
+                    }
+                    self.dirty = true;
+                }
+                None => self.post_failed(&id, &to, now_ms),
+            },
+            NetOp::Fetch { relay } => {
+                match body.and_then(|b| serde_json::from_slice::<MailboxPage>(&b).ok()) {
+                    Some(page) => self.receive_page(&relay, page, now_ms),
+                    None => syscall::debug(&format!(
+                        "MessagingService: Mailbox read from {} failed",
+                        relay
+                    )),
+                }
+            }
+        }
+        self.tick(now_ms);
+        Ok(())
+    }
+
+    /// Open the envelopes of a mailbox page and move the relay's cursor on.
+    fn receive_page(&mut self, relay: &str, page: MailboxPage, now_ms: u64) {
+        let full = page.envelopes.len() >= MAX_PAGE_ENVELOPES;
+        for envelope in page.envelopes.into_iter().take(MAX_PAGE_ENVELOPES) {
+            self.receive_envelope(envelope, now_ms);
+        }
+        if let Some(cursor) = page.cursor {
+            self.store.set_cursor(relay, &cursor);
+            self.dirty = true;
+            // More may be waiting: read the next page right away
+            if full {
+                self.next_poll_ms = now_ms;
+            }
+        }
+    }
+
+    /// Open one envelope from a mailbox.
+    fn receive_envelope(&mut self, envelope: Envelope, now_ms: u64) {
+        let Some(account) = &self.account else {
+            return;
+        };
+        let (from, payload) = match envelope::open(&account.secret, &envelope) {
+            Ok(opened) => opened,
+            Err(e) => {
+                syscall::debug(&format!(
+                    "MessagingService: Dropping envelope {}: {}",
+                    envelope.id, e
+                ));
+                return;
+            }
+        };
+        // Rule 10: only the user's machines and contacts may write here
+        let Some(peer) = self.peer_for(&from) else {
+            syscall::debug(&format!(
+                "MessagingService: SECURITY - Dropping envelope {} from unknown key {}",
+                envelope.id, envelope.from
+            ));
+            return;
+        };
+
+        match payload {
+            Payload::Text { body, sent_at_ms } => {
+                if body.is_empty() || body.len() > MAX_BODY_LEN {
+                    return;
+                }
+                let record = MessageRecord {
+                    id: envelope.id.clone(),
+                    peer: peer.clone(),
+                    direction: Direction::Incoming,
+                    body,
+                    sent_at_ms,
+                    status: MessageStatus::Received,
+                    updated_at_ms: now_ms,
+                    sender_key: Some(envelope.from.clone()),
+                };
+                if let Some(record) = self.store.receive(record) {
+                    self.dirty = true;
+                    self.notify(&record);
+                    self.publish(
+                        "messaging/received",
+                        serde_json::json!({ "id": record.id, "peer": record.peer }),
+                    );
+                }
+                // Acknowledge duplicates too: the first receipt may have been lost
+                self.queue_receipt(&peer, &from, &envelope.id, ReceiptStatus::Delivered, now_ms);
+            }
+            Payload::Receipt { of, status } => {
+                if self.store.get(&of).is_none_or(|m| m.peer != peer) {
+                    syscall::debug(&format!(
+                        "MessagingService: SECURITY - Ignoring receipt for {} from another peer",
+                        of
+                    ));
+                    return;
+                }
+                if let Some(record) = self.store.apply_receipt(&of, status, now_ms) {
+                    self.dirty = true;
+                    self.publish_status(&record);
+                }
+            }
+            Payload::Clipboard { text, copied_at_ms } => {
+                if peer != Peer::Machines {
+                    syscall::debug(&format!(
+                        "MessagingService: SECURITY - Dropping clipboard entry {} from a contact",
+                        envelope.id
+                    ));
+                    return;
+                }
+                if text.is_empty() || text.len() > MAX_BODY_LEN {
+                    return;
+                }
+                let json =
+                    serde_json::to_vec(&ClipboardEntry { text, copied_at_ms }).unwrap_or_default();
+                if let Err(e) =
+                    syscall::send_named("clipboard", clipboard::MSG_CLIPBOARD_REMOTE, &json)
+                {
+                    syscall::debug(&format!(
+                        "MessagingService: Clipboard entry {} not handed on: {}",
+                        envelope.id, e
+                    ));
+                }
+            }
+        }
+    }
+
+    // =========================================================================
+    // Notifications and events
+    // =========================================================================
+
+    /// Show a desktop notification for an incoming message.
+    fn notify(&self, record: &MessageRecord) {
+        let title = match &record.peer {
+            Peer::Contact(id) => self
+                .contacts
+                .get(id)
+                .map(|route| route.name.clone())
+                .unwrap_or_else(|| String::from("New message")),
+            Peer::Machines => String::from("From your other machine"),
+        };
+        let mut body: String = record.body.chars().take(PREVIEW_CHARS).collect();
+        if body.len() < record.body.len() {
+            body.push('…');
+        }
+        let notification = Notification {
+            title,
+            body,
+            source: String::from("messaging"),
+            tag: format!("message-{}", record.id),
+        };
+        let json = serde_json::to_vec(&notification).unwrap_or_default();
+        let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+        syscall::debug(&format!("{}{}", debug::NOTIFY_SHOW, hex));
+    }
+
+    /// Publish a message's new status on the event bus.
+    fn publish_status(&self, record: &MessageRecord) {
+        self.publish(
+            "messaging/status",
+            serde_json::json!({ "id": record.id, "status": record.status }),
+        );
+    }
+
+    /// Publish on the event bus. Payloads name messages, never their bodies.
+    fn publish(&self, topic: &str, payload: serde_json::Value) {
+        let json = serde_json::to_vec(&serde_json::json!({ "topic": topic, "payload": payload }))
+            .unwrap_or_default();
+        if let Err(e) = syscall::send_named("events", events::MSG_EVENT_PUBLISH, &json) {
+            syscall::debug(&format!(
+                "MessagingService: Publish to {} failed: {}",
+                topic, e
+            ));
+        }
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Handle a VFS response to one of our tasks. Returns false if the
+    /// message isn't one.
+    fn handle_vfs_response(&mut self, msg: &Message) -> bool {
+        if !self.vfs.on_message(msg.tag, &msg.data) {
+            return false;
+        }
+        self.finish_tasks();
+        true
+    }
+
+    /// Handle the outcome of every finished VFS task.
+    fn finish_tasks(&mut self) {
+        while let Some(done) = self.vfs.next_finished() {
+            match done {
+                Done::Loaded {
+                    client_pid,
+                    cap_slots,
+                    relays,
+                    result,
+                } => {
+                    if let Err(e) = self.store_loaded(client_pid, &cap_slots, relays, result) {
+                        syscall::debug(&format!(
+                            "MessagingService: Failed to answer open from PID {}: {:?}",
+                            client_pid, e
+                        ));
+                    }
+                }
+
+                Done::Saved(result) => {
+                    self.saving = self.saving.saturating_sub(1);
+                    if let Err(e) = result {
+                        syscall::debug(&format!(
+                            "MessagingService: Failed to write messages: {}",
+                            e
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Finish an OPEN once the message store has been read.
+    fn store_loaded(
+        &mut self,
+        client_pid: u32,
+        cap_slots: &[u32],
+        relays: Option<Vec<String>>,
+        result: Result<Vec<u8>, String>,
+    ) -> Result<(), AppError> {
+        if self.account.is_none() {
+            return Ok(());
+        }
+
+        match result.ok().and_then(|data| MessageStore::from_json(&data)) {
+            Some(stored) => self.store = stored,
+            None => syscall::debug("MessagingService: No stored messages found"),
+        }
+        if let Some(relays) = relays {
+            // Checked when the open was requested
+            let _ = self.store.set_relays(relays);
+            self.dirty = true;
+        }
+        self.loaded = true;
+        self.next_poll_ms = 0;
+
+        let Some(account) = &self.account else {
+            return Ok(());
+        };
+        let response = OpenResponse {
+            key: account.key.clone(),
+            fingerprint: key_fingerprint(&envelope::public_key(&account.secret)),
+            machines: account.machines.len() as u32,
+            relays: self.store.relays().to_vec(),
+        };
+        syscall::debug(&format!(
+            "MessagingService: Opened mailbox with {} other machines",
+            response.machines
+        ));
+        self.refresh_contacts();
+        let json = serde_json::to_vec(&response).unwrap_or_default();
+        self.send_response(
+            client_pid,
+            cap_slots,
+            messaging_msg::MSG_MESSAGING_OPEN_RESPONSE,
+            &json,
+        )
+    }
+
+    // =========================================================================
+    // Response helpers
+    // =========================================================================
+
+    fn send_record_response(
+        &self,
+        to_pid: u32,
+        cap_slots: &[u32],
+        result: Result<MessageRecord, String>,
+    ) -> Result<(), AppError> {
+        let tag = messaging_msg::MSG_MESSAGING_SEND_RESPONSE;
+        match result {
+            Ok(record) => {
+                let json = serde_json::to_vec(&record).unwrap_or_default();
+                self.send_response(to_pid, cap_slots, tag, &json)
+            }
+            Err(e) => self.send_error_response(to_pid, cap_slots, tag, &e),
+        }
+    }
+}
+
+impl JsonResponder for MessagingService {
+    const SERVICE_NAME: &'static str = "MessagingService";
+}
+
+/// A fresh random message ID: 16 bytes, hex.
+fn new_message_id() -> Result<String, String> {
+    let mut id = [0u8; 16];
+    getrandom::getrandom(&mut id).map_err(|e| format!("Random number generation failed: {}", e))?;
+    Ok(bytes_to_hex(&id))
+}
+
+impl ZeroApp for MessagingService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &MESSAGING_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("MessagingService starting (PID {})", ctx.pid));
+
+        // Register with init as "messaging" service
+        let service_name = "messaging";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("MessagingService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &AppContext) -> ControlFlow {
+        self.tick(ctx.wallclock_ms);
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        // VFS responses (Invariant 31 compliant - storage via VFS IPC)
+        if self.handle_vfs_response(&msg) {
+            return Ok(());
+        }
+
+        match msg.tag {
+            // Replies from other services
+            keystore_svc::MSG_KEYSTORE_READ_RESPONSE | keystore_svc::MSG_KEYSTORE_LIST_RESPONSE => {
+                self.handle_keystore_response(&msg)
+            }
+            contacts::MSG_CONTACT_LIST_RESPONSE => self.handle_contact_list(&msg, ctx.wallclock_ms),
+            net::MSG_NET_RESPONSE => self.handle_net_response(&msg, ctx.wallclock_ms),
+            events::MSG_EVENT_PUBLISH_RESPONSE => Ok(()),
+
+            // Messaging service protocol
+            messaging_msg::MSG_MESSAGING_OPEN => self.handle_open(&msg),
+            messaging_msg::MSG_MESSAGING_SEND => self.handle_send(&msg, ctx.wallclock_ms),
+            messaging_msg::MSG_MESSAGING_LIST => self.handle_list(&msg),
+            messaging_msg::MSG_MESSAGING_MARK_READ => self.handle_mark_read(&msg, ctx.wallclock_ms),
+            messaging_msg::MSG_MESSAGING_SYNC => self.handle_sync(&msg),
+            messaging_msg::MSG_MESSAGING_SEND_CLIPBOARD => {
+                self.handle_send_clipboard(&msg, ctx.wallclock_ms)
+            }
+
+            _ => {
+                syscall::debug(&format!(
+                    "MessagingService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("MessagingService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+
+    const ME: [u8; 32] = [1; 32];
+    const LAPTOP: [u8; 32] = [2; 32];
+    const ALICE: [u8; 32] = [3; 32];
+    const RELAY: &str = "wss://relay.example.com";
+
+    /// A service with an open, loaded mailbox, another machine and a contact.
+    fn opened() -> MessagingService {
+        let mut service = MessagingService {
+            account: Some(Account {
+                user_id: 1,
+                secret: ME,
+                key: bytes_to_hex(&envelope::public_key(&ME)),
+                machines: vec![envelope::public_key(&LAPTOP)],
+            }),
+            loaded: true,
+            ..Default::default()
+        };
+        service.store.set_relays(vec![String::from(RELAY)]).unwrap();
+        service.contacts.insert(
+            5,
+            ContactRoute {
+                name: String::from("Alice"),
+                key: Some(envelope::public_key(&ALICE)),
+                relays: vec![String::from("wss://alice.example.com")],
+            },
+        );
+        service
+    }
+
+    fn send(service: &mut MessagingService, json: &str) {
+        let msg = mock_message(
+            messaging_msg::MSG_MESSAGING_SEND,
+            7,
+            json.as_bytes().to_vec(),
+        );
+        service.handle_send(&msg, 1_000).unwrap();
+    }
+
+    #[test]
+    fn test_requests_refused_until_open() {
+        let mut service = MessagingService::default();
+        send(&mut service, r#"{"to":"machines","body":"hi"}"#);
+        assert_eq!(service.store.outbox_len(), 0);
+        assert!(service.store.list(None, 10).is_empty());
+    }
+
+    #[test]
+    fn test_send_seals_for_each_machine() {
+        let mut service = opened();
+        send(&mut service, r#"{"to":"machines","body":"hi"}"#);
+        assert_eq!(service.store.outbox_len(), 1);
+        assert!(service.dirty);
+
+        let entry = service.store.next_due(1_000).unwrap().clone();
+        let (from, payload) = envelope::open(&LAPTOP, &entry.envelope).unwrap();
+        assert_eq!(from, envelope::public_key(&ME));
+        assert!(matches!(payload, Payload::Text { body, .. } if body == "hi"));
+    }
+
+    #[test]
+    fn test_store_written_one_write_at_a_time() {
+        let mut service = opened();
+        send(&mut service, r#"{"to":"machines","body":"hi"}"#);
+        service.save_store();
+        assert_eq!((service.saving, service.dirty), (1, false));
+
+        // Later changes wait for the write in flight
+        send(&mut service, r#"{"to":"machines","body":"again"}"#);
+        service.save_store();
+        assert_eq!((service.saving, service.dirty), (1, true));
+
+        let written = mock_message(
+            zos_vfs::ipc::vfs_msg::MSG_VFS_WRITE_RESPONSE,
+            4,
+            br#"{"result":{"Ok":null}}"#.to_vec(),
+        );
+        assert!(service.handle_vfs_response(&written));
+        assert_eq!(service.saving, 0);
+        service.save_store();
+        assert_eq!((service.saving, service.dirty), (1, false));
+    }
+
+    #[test]
+    fn test_clipboard_sent_only_for_clipboard_service() {
+        let mut service = opened();
+        let json = br#"{"text":"copied","copied_at_ms":900}"#.to_vec();
+        let msg = mock_message(messaging_msg::MSG_MESSAGING_SEND_CLIPBOARD, 8, json.clone());
+        service.handle_send_clipboard(&msg, 1_000).unwrap();
+        assert_eq!(service.store.outbox_len(), 0);
+
+        service.names.remember(8, "clipboard");
+        service.handle_send_clipboard(&msg, 1_000).unwrap();
+        assert_eq!(service.store.outbox_len(), 1);
+        // Kept out of the message history
+        assert!(service.store.list(None, 10).is_empty());
+
+        let entry = service.store.next_due(1_000).unwrap().clone();
+        assert_eq!(entry.message, None);
+        let (_, payload) = envelope::open(&LAPTOP, &entry.envelope).unwrap();
+        assert_eq!(
+            payload,
+            Payload::Clipboard {
+                text: String::from("copied"),
+                copied_at_ms: 900
+            }
+        );
+    }
+
+    #[test]
+    fn test_clipboard_from_contact_dropped() {
+        let mut service = opened();
+        let clip = Payload::Clipboard {
+            text: String::from("copied"),
+            copied_at_ms: 5,
+        };
+        let to = envelope::public_key(&ME);
+        let page = MailboxPage {
+            envelopes: vec![envelope::seal(&ALICE, &to, "c1", &clip, &[7; 44]).unwrap()],
+            cursor: None,
+        };
+        service.receive_page(RELAY, page, 1_000);
+        // No message and no receipt
+        assert!(service.store.list(None, 10).is_empty());
+        assert_eq!(service.store.outbox_len(), 0);
+    }
+
+    #[test]
+    fn test_send_to_unknown_contact_waits_for_list() {
+        let mut service = opened();
+        send(&mut service, r#"{"to":{"contact":9},"body":"hi"}"#);
+        assert_eq!(service.parked.len(), 1);
+        assert_eq!(service.store.outbox_len(), 0);
+
+        let key = bytes_to_hex(&envelope::public_key(&ALICE));
+        let list = format!(
+            r#"{{"contacts":[{{"id":9,"name":"Bob","encryption_key":"{}","endpoints":[{{"kind":"relay","address":"wss://bob.example.com"}}]}}]}}"#,
+            key
+        );
+        let msg = mock_message(contacts::MSG_CONTACT_LIST_RESPONSE, 3, list.into_bytes());
+        service.handle_contact_list(&msg, 1_000).unwrap();
+        assert!(service.parked.is_empty());
+        assert_eq!(service.store.outbox_len(), 1);
+    }
+
+    #[test]
+    fn test_incoming_message_acknowledged() {
+        let mut service = opened();
+        let to = envelope::public_key(&ME);
+        let text = Payload::Text {
+            body: String::from("hello"),
+            sent_at_ms: 5,
+        };
+        let from_alice = envelope::seal(&ALICE, &to, "m1", &text, &[7; 44]).unwrap();
+        let from_stranger = envelope::seal(&[4; 32], &to, "m2", &text, &[7; 44]).unwrap();
+        let page = MailboxPage {
+            envelopes: vec![from_alice, from_stranger],
+            cursor: Some(String::from("c1")),
+        };
+        service.receive_page(RELAY, page, 1_000);
+
+        let messages = service.store.list(None, 10);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].peer, Peer::Contact(5));
+        assert_eq!(service.store.cursor(RELAY), Some("c1"));
+
+        // A delivery receipt goes back to Alice
+        let receipt = service.store.next_due(1_000).unwrap().clone();
+        let (_, payload) = envelope::open(&ALICE, &receipt.envelope).unwrap();
+        assert_eq!(
+            payload,
+            Payload::Receipt {
+                of: String::from("m1"),
+                status: ReceiptStatus::Delivered
+            }
+        );
+    }
+}
@@ -0,0 +1,544 @@
+//! Message store
+//!
+//! Each user's messages live in `/var/lib/messaging/{user_id}.json`,
+//! together with everything needed to carry on where the service left
+//! off:
+//!
+//! - the history: messages sent and received, oldest first, capped at
+//!   [`MAX_MESSAGES`]
+//! - the outbox: sealed envelopes not yet accepted by a relay, retried
+//!   with backoff until [`OUTBOX_TTL_MS`] has passed
+//! - the user's relays and how far each mailbox has been read
+//!
+//! A message to several keys (the user's other machines) has one outbox
+//! entry per key and counts as sent once the last of them is posted.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use super::envelope::{Envelope, ReceiptStatus};
+
+/// Maximum messages kept; the oldest are dropped first (Rule 11)
+pub const MAX_MESSAGES: usize = 2000;
+
+/// Maximum envelopes waiting to be posted (Rule 11)
+pub const MAX_OUTBOX: usize = 256;
+
+/// Maximum message body length in bytes
+pub const MAX_BODY_LEN: usize = 16 * 1024;
+
+/// Maximum relays per user
+pub const MAX_RELAYS: usize = 4;
+
+/// Maximum relay URL length in bytes
+pub const MAX_RELAY_LEN: usize = 512;
+
+/// Maximum stored mailbox cursor length in bytes
+pub const MAX_CURSOR_LEN: usize = 128;
+
+/// How long an envelope is retried before its message fails (7 days)
+pub const OUTBOX_TTL_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Delay before the first retry; doubled per attempt
+const RETRY_BASE_MS: u64 = 5_000;
+
+/// Longest delay between retries (10 minutes)
+const RETRY_MAX_MS: u64 = 10 * 60 * 1000;
+
+/// Storage path of a user's messages.
+pub fn store_path(user_id: u128) -> String {
+    format!("/var/lib/messaging/{:032x}.json", user_id)
+}
+
+/// Check a relay URL: `wss://`, as in contact endpoints.
+pub fn validate_relay(relay: &str) -> Result<(), String> {
+    if !relay.starts_with("wss://") || relay.len() <= "wss://".len() {
+        return Err(format!("Relays must be wss:// URLs: {}", relay));
+    }
+    if relay.len() > MAX_RELAY_LEN {
+        return Err(format!("Relay URL too long (max {} bytes)", MAX_RELAY_LEN));
+    }
+    Ok(())
+}
+
+/// HTTPS URL of `key`'s mailbox on `relay`.
+///
+/// Relays are named by the WebSocket URL machines keep open to them;
+/// mailboxes are posted to and read over HTTPS on the same host.
+pub fn mailbox_url(relay: &str, key: &str) -> String {
+    let base = relay.strip_prefix("wss://").unwrap_or(relay);
+    format!("https://{}/v1/mailbox/{}", base.trim_end_matches('/'), key)
+}
+
+/// Who a conversation is with.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Peer {
+    /// A contact from the address book, by ID
+    Contact(u32),
+    /// The user's own enrolled machines
+    Machines,
+}
+
+/// Which way a message went.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Outgoing,
+    Incoming,
+}
+
+/// Where a message is at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageStatus {
+    /// Outgoing, waiting in the outbox
+    Queued,
+    /// Outgoing, accepted by the relay
+    Sent,
+    /// Outgoing, stored by the recipient
+    Delivered,
+    /// Outgoing, read by the recipient; incoming, read by the user
+    Read,
+    /// Outgoing, given up on after [`OUTBOX_TTL_MS`]
+    Failed,
+    /// Incoming, not read yet
+    Received,
+}
+
+/// A message in the history.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageRecord {
+    pub id: String,
+    pub peer: Peer,
+    pub direction: Direction,
+    pub body: String,
+    /// When the sender wrote it, ms since the Unix epoch
+    pub sent_at_ms: u64,
+    pub status: MessageStatus,
+    /// When the status last changed
+    pub updated_at_ms: u64,
+    /// Key an incoming message came from, which receipts go back to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_key: Option<String>,
+}
+
+/// A sealed envelope waiting to be posted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub envelope: Envelope,
+    /// Relays the recipient reads; tried in turn
+    pub relays: Vec<String>,
+    /// Message this delivers; receipts and clipboard entries have none
+    #[serde(default)]
+    pub message: Option<String>,
+    pub queued_at_ms: u64,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub next_attempt_ms: u64,
+}
+
+impl OutboxEntry {
+    /// Relay the next attempt goes to.
+    pub fn relay(&self) -> Option<&str> {
+        if self.relays.is_empty() {
+            return None;
+        }
+        Some(&self.relays[self.attempts as usize % self.relays.len()])
+    }
+
+    fn is(&self, id: &str, to: &str) -> bool {
+        self.envelope.id == id && self.envelope.to == to
+    }
+}
+
+/// A user's messages, outbox and relays.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageStore {
+    #[serde(default)]
+    relays: Vec<String>,
+    /// Relay -> cursor of the last mailbox page read
+    #[serde(default)]
+    cursors: BTreeMap<String, String>,
+    #[serde(default)]
+    messages: Vec<MessageRecord>,
+    #[serde(default)]
+    outbox: Vec<OutboxEntry>,
+}
+
+impl MessageStore {
+    /// Serialize to JSON bytes
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Parse stored JSON; `None` if it isn't a message store.
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+
+    pub fn relays(&self) -> &[String] {
+        &self.relays
+    }
+
+    /// Replace the relays. Cursors of relays no longer used are dropped.
+    pub fn set_relays(&mut self, relays: Vec<String>) -> Result<(), String> {
+        if relays.len() > MAX_RELAYS {
+            return Err(format!("Too many relays (max {})", MAX_RELAYS));
+        }
+        for relay in &relays {
+            validate_relay(relay)?;
+        }
+        self.cursors.retain(|relay, _| relays.contains(relay));
+        self.relays = relays;
+        Ok(())
+    }
+
+    /// Cursor to read `relay`'s mailbox from.
+    pub fn cursor(&self, relay: &str) -> Option<&str> {
+        self.cursors.get(relay).map(String::as_str)
+    }
+
+    /// Record how far `relay`'s mailbox has been read. Cursors that can't
+    /// go in a URL query as they are, or are too long, are not kept.
+    pub fn set_cursor(&mut self, relay: &str, cursor: &str) {
+        let usable = !cursor.is_empty()
+            && cursor.len() <= MAX_CURSOR_LEN
+            && cursor
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if usable && self.relays.iter().any(|r| r == relay) {
+            self.cursors
+                .insert(String::from(relay), String::from(cursor));
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&MessageRecord> {
+        self.messages.iter().find(|m| m.id == id)
+    }
+
+    /// The last `limit` messages, optionally with one peer, oldest first.
+    pub fn list(&self, peer: Option<&Peer>, limit: usize) -> Vec<MessageRecord> {
+        let matching: Vec<&MessageRecord> = self
+            .messages
+            .iter()
+            .filter(|m| peer.is_none_or(|p| m.peer == *p))
+            .collect();
+        let skip = matching.len().saturating_sub(limit);
+        matching.into_iter().skip(skip).cloned().collect()
+    }
+
+    /// Add a message to the history, dropping the oldest beyond the cap.
+    fn push(&mut self, record: MessageRecord) {
+        self.messages.push(record);
+        if self.messages.len() > MAX_MESSAGES {
+            let excess = self.messages.len() - MAX_MESSAGES;
+            self.messages.drain(..excess);
+        }
+    }
+
+    /// Record a message being sent, with its envelopes. Either both are
+    /// stored or, if the outbox has no room, neither.
+    pub fn send(&mut self, record: MessageRecord, entries: Vec<OutboxEntry>) -> Result<(), String> {
+        if self.outbox.len() + entries.len() > MAX_OUTBOX {
+            return Err(format!(
+                "Outbox full ({} envelopes waiting)",
+                self.outbox.len()
+            ));
+        }
+        self.push(record);
+        self.outbox.extend(entries);
+        Ok(())
+    }
+
+    /// Queue a receipt. Receipts are dropped rather than crowd out
+    /// messages when the outbox is nearly full.
+    pub fn queue_receipt(&mut self, entry: OutboxEntry) -> bool {
+        if self.outbox.len() >= MAX_OUTBOX / 2 {
+            return false;
+        }
+        self.outbox.push(entry);
+        true
+    }
+
+    /// Queue a clipboard entry's envelopes, all of them or none. Like
+    /// receipts, they give way to messages when the outbox is nearly full.
+    pub fn queue_clipboard(&mut self, entries: Vec<OutboxEntry>) -> bool {
+        if self.outbox.len() + entries.len() > MAX_OUTBOX / 2 {
+            return false;
+        }
+        self.outbox.extend(entries);
+        true
+    }
+
+    /// Record a received message. Returns it, or `None` if it was already
+    /// received (relays may deliver an envelope more than once).
+    pub fn receive(&mut self, record: MessageRecord) -> Option<MessageRecord> {
+        if self.get(&record.id).is_some() {
+            return None;
+        }
+        self.push(record.clone());
+        Some(record)
+    }
+
+    /// Number of envelopes in the outbox.
+    pub fn outbox_len(&self) -> usize {
+        self.outbox.len()
+    }
+
+    /// The oldest envelope due to be posted at `now_ms`.
+    pub fn next_due(&self, now_ms: u64) -> Option<&OutboxEntry> {
+        self.outbox.iter().find(|e| e.next_attempt_ms <= now_ms)
+    }
+
+    /// Make every envelope due now.
+    pub fn retry_now(&mut self) {
+        for entry in &mut self.outbox {
+            entry.next_attempt_ms = 0;
+        }
+    }
+
+    /// A relay accepted envelope `id` for `to`. Returns the message if it
+    /// is now sent.
+    pub fn posted(&mut self, id: &str, to: &str, now_ms: u64) -> Option<MessageRecord> {
+        let index = self.outbox.iter().position(|e| e.is(id, to))?;
+        let message = self.outbox.remove(index).message?;
+        if self
+            .outbox
+            .iter()
+            .any(|e| e.message.as_ref() == Some(&message))
+        {
+            return None;
+        }
+        self.set_status(&message, MessageStatus::Sent, now_ms)
+    }
+
+    /// Posting envelope `id` for `to` failed. It is tried again later, on
+    /// the next relay, until it expires; returns its message if that has
+    /// now failed.
+    pub fn post_failed(&mut self, id: &str, to: &str, now_ms: u64) -> Option<MessageRecord> {
+        let index = self.outbox.iter().position(|e| e.is(id, to))?;
+        let entry = &mut self.outbox[index];
+        if now_ms.saturating_sub(entry.queued_at_ms) < OUTBOX_TTL_MS {
+            entry.attempts = entry.attempts.saturating_add(1);
+            let delay = RETRY_BASE_MS
+                .saturating_mul(1u64 << entry.attempts.min(16))
+                .min(RETRY_MAX_MS);
+            entry.next_attempt_ms = now_ms.saturating_add(delay);
+            return None;
+        }
+        let message = self.outbox.remove(index).message?;
+        // The message's other envelopes are abandoned with it
+        self.outbox.retain(|e| e.message.as_ref() != Some(&message));
+        self.set_status(&message, MessageStatus::Failed, now_ms)
+    }
+
+    /// Apply a receipt for one of our messages. Statuses only move
+    /// forward, so a late "delivered" doesn't undo "read".
+    pub fn apply_receipt(
+        &mut self,
+        of: &str,
+        status: ReceiptStatus,
+        now_ms: u64,
+    ) -> Option<MessageRecord> {
+        let status = match status {
+            ReceiptStatus::Delivered => MessageStatus::Delivered,
+            ReceiptStatus::Read => MessageStatus::Read,
+        };
+        let record = self.get(of)?;
+        let forward = record.direction == Direction::Outgoing
+            && record.status != MessageStatus::Failed
+            && record.status < status;
+        if !forward {
+            return None;
+        }
+        // A receipt means the recipient has it, whatever the outbox says
+        self.outbox.retain(|e| e.message.as_deref() != Some(of));
+        self.set_status(of, status, now_ms)
+    }
+
+    /// Mark received messages read. Returns those that weren't already.
+    pub fn mark_read(&mut self, ids: &[String], now_ms: u64) -> Vec<MessageRecord> {
+        let mut marked = Vec::new();
+        for record in &mut self.messages {
+            if record.status == MessageStatus::Received && ids.contains(&record.id) {
+                record.status = MessageStatus::Read;
+                record.updated_at_ms = now_ms;
+                marked.push(record.clone());
+            }
+        }
+        marked
+    }
+
+    fn set_status(
+        &mut self,
+        id: &str,
+        status: MessageStatus,
+        now_ms: u64,
+    ) -> Option<MessageRecord> {
+        let record = self.messages.iter_mut().find(|m| m.id == id)?;
+        record.status = status;
+        record.updated_at_ms = now_ms;
+        Some(record.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELAY: &str = "wss://relay.example";
+
+    fn outgoing(id: &str) -> MessageRecord {
+        MessageRecord {
+            id: String::from(id),
+            peer: Peer::Machines,
+            direction: Direction::Outgoing,
+            body: String::from("hi"),
+            sent_at_ms: 1,
+            status: MessageStatus::Queued,
+            updated_at_ms: 1,
+            sender_key: None,
+        }
+    }
+
+    fn entry(id: &str, to: &str, message: Option<&str>) -> OutboxEntry {
+        OutboxEntry {
+            envelope: Envelope {
+                version: 1,
+                id: String::from(id),
+                from: String::from("aa"),
+                to: String::from(to),
+                ephemeral: String::from("bb"),
+                nonce: String::from("cc"),
+                ciphertext: String::from("dd"),
+            },
+            relays: alloc::vec![String::from(RELAY)],
+            message: message.map(String::from),
+            queued_at_ms: 0,
+            attempts: 0,
+            next_attempt_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_sent_once_every_envelope_posted() {
+        let mut store = MessageStore::default();
+        store
+            .send(
+                outgoing("m1"),
+                alloc::vec![entry("m1", "k1", Some("m1")), entry("m1", "k2", Some("m1"))],
+            )
+            .unwrap();
+        assert_eq!(store.outbox_len(), 2);
+
+        assert!(store.posted("m1", "k1", 5).is_none());
+        assert_eq!(store.get("m1").unwrap().status, MessageStatus::Queued);
+        let sent = store.posted("m1", "k2", 6).unwrap();
+        assert_eq!(sent.status, MessageStatus::Sent);
+        assert_eq!(store.outbox_len(), 0);
+    }
+
+    #[test]
+    fn test_retry_backoff_and_expiry() {
+        let mut store = MessageStore::default();
+        store
+            .send(outgoing("m1"), alloc::vec![entry("m1", "k1", Some("m1"))])
+            .unwrap();
+
+        assert!(store.post_failed("m1", "k1", 1_000).is_none());
+        assert!(store.next_due(1_000).is_none());
+        assert!(store.next_due(1_000 + RETRY_BASE_MS * 2).is_some());
+        store.retry_now();
+        assert!(store.next_due(1_000).is_some());
+
+        let failed = store.post_failed("m1", "k1", OUTBOX_TTL_MS).unwrap();
+        assert_eq!(failed.status, MessageStatus::Failed);
+        assert_eq!(store.outbox_len(), 0);
+        // A receipt can't revive a failed message
+        assert!(store
+            .apply_receipt("m1", ReceiptStatus::Read, OUTBOX_TTL_MS)
+            .is_none());
+    }
+
+    #[test]
+    fn test_receipts_only_move_forward() {
+        let mut store = MessageStore::default();
+        store.send(outgoing("m1"), Vec::new()).unwrap();
+        let read = store.apply_receipt("m1", ReceiptStatus::Read, 5).unwrap();
+        assert_eq!(read.status, MessageStatus::Read);
+        assert!(store
+            .apply_receipt("m1", ReceiptStatus::Delivered, 6)
+            .is_none());
+        assert!(store
+            .apply_receipt("unknown", ReceiptStatus::Read, 6)
+            .is_none());
+    }
+
+    #[test]
+    fn test_receive_dedupes_and_mark_read() {
+        let mut store = MessageStore::default();
+        let incoming = MessageRecord {
+            direction: Direction::Incoming,
+            status: MessageStatus::Received,
+            peer: Peer::Contact(3),
+            ..outgoing("m9")
+        };
+        assert!(store.receive(incoming.clone()).is_some());
+        assert!(store.receive(incoming).is_none());
+
+        let ids = alloc::vec![String::from("m9")];
+        assert_eq!(store.mark_read(&ids, 7).len(), 1);
+        assert!(store.mark_read(&ids, 8).is_empty());
+        assert_eq!(store.list(Some(&Peer::Contact(3)), 10).len(), 1);
+        assert!(store.list(Some(&Peer::Machines), 10).is_empty());
+    }
+
+    #[test]
+    fn test_relays_and_cursors() {
+        let mut store = MessageStore::default();
+        assert!(store
+            .set_relays(alloc::vec![String::from("https://relay.example")])
+            .is_err());
+        store.set_relays(alloc::vec![String::from(RELAY)]).unwrap();
+
+        store.set_cursor(RELAY, "42-abc");
+        store.set_cursor(RELAY, "bad&cursor");
+        store.set_cursor("wss://other.example", "7");
+        assert_eq!(store.cursor(RELAY), Some("42-abc"));
+        assert_eq!(store.cursor("wss://other.example"), None);
+
+        assert_eq!(
+            mailbox_url("wss://relay.example/", "k1"),
+            "https://relay.example/v1/mailbox/k1"
+        );
+
+        let restored = MessageStore::from_json(&store.to_json()).unwrap();
+        assert_eq!(restored, store);
+        store.set_relays(Vec::new()).unwrap();
+        assert_eq!(store.cursor(RELAY), None);
+    }
+
+    #[test]
+    fn test_clipboard_gives_way_to_messages() {
+        let mut store = MessageStore::default();
+        let waiting: Vec<OutboxEntry> = (0..MAX_OUTBOX / 2 - 1)
+            .map(|i| entry("m1", &format!("k{}", i), Some("m1")))
+            .collect();
+        store.send(outgoing("m1"), waiting).unwrap();
+
+        // Both envelopes or neither
+        let clip = alloc::vec![entry("c1", "k1", None), entry("c1", "k2", None)];
+        assert!(!store.queue_clipboard(clip));
+        assert_eq!(store.outbox_len(), MAX_OUTBOX / 2 - 1);
+        assert!(store.queue_clipboard(alloc::vec![entry("c2", "k1", None)]));
+
+        // Posting a clipboard envelope changes no message
+        assert!(store.posted("c2", "k1", 5).is_none());
+        assert_eq!(store.get("m1").unwrap().status, MessageStatus::Queued);
+    }
+}
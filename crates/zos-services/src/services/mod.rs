@@ -12,18 +12,54 @@
 //! - **time**: Time settings management (PID 6)
 //! - **network**: HTTP request mediation (PID 8)
 //! - **keystore**: Cryptographic key storage (PID 7)
+//! - **update**: A/B system updates with boot-health rollback
+//! - **flags**: Persistent feature flags with per-user overrides
+//! - **speech**: Screen reader speech queue with per-app mute
+//! - **events**: Topic-based publish/subscribe with retained events
+//! - **calendar**: Shared calendar events with recurrence, reminders and ICS import
+//! - **contacts**: Address book with fingerprint-verified keys and card export/import
+//! - **messaging**: End-to-end encrypted messages via relays, with offline queueing and receipts
+//! - **clipboard**: Clipboard history with opt-in encrypted sync to the user's other machines
+//! - **print**: Print jobs to the print dialog or to PDF, with status events
+//! - **thumbnail**: Image thumbnails decoded by the supervisor, cached by content hash
+//! - **archive**: Zip and tar archive creation and extraction, streamed through VFS file handles
+//! - **commands**: App-registered commands with fuzzy search for the command palette
 
+pub mod archive;
+pub mod calendar;
+pub mod clipboard;
+pub mod commands;
+pub mod contacts;
+pub mod events;
+pub mod flags;
 pub mod identity;
 pub mod keystore;
+pub mod messaging;
 pub mod network;
 pub mod permission;
+pub mod print;
+pub mod speech;
+pub mod thumbnail;
 pub mod time;
+pub mod update;
 pub mod vfs;
 
 // Re-export service types for convenience
+pub use archive::ArchiveService;
+pub use calendar::CalendarService;
+pub use clipboard::ClipboardService;
+pub use commands::CommandService;
+pub use contacts::AddressBookService;
+pub use events::EventBusService;
+pub use flags::FeatureFlagService;
 pub use identity::IdentityService;
 pub use keystore::KeystoreService;
+pub use messaging::MessagingService;
 pub use network::NetworkService;
 pub use permission::PermissionService;
+pub use print::PrintService;
+pub use speech::SpeechService;
+pub use thumbnail::ThumbnailService;
 pub use time::TimeService;
+pub use update::UpdateService;
 pub use vfs::VfsService;
@@ -0,0 +1,416 @@
+//! Response cache handlers
+//!
+//! The index is loaded from [`INDEX_PATH`] at startup, alongside the policy,
+//! and requests are deferred until both are in. Cache reads and writes
+//! never hold up a response: a body that can't be read is refetched, and
+//! one that can't be written is simply not cached.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_network::cache::{
+    self as http_cache, body_path, CacheEntry, CacheIndex, CacheLookup, CacheStats, CACHE_DIR,
+    INDEX_PATH,
+};
+use zos_network::{HttpRequest, HttpResponse, HttpSuccess};
+use zos_process::net;
+
+use super::{NetworkService, PendingRequest, VfsOp, MAX_VFS_OPS};
+
+/// What to do with a response once it arrives.
+#[derive(Clone, Debug)]
+pub(super) enum CachePlan {
+    /// Forward it untouched
+    Bypass,
+    /// Forward it, storing it if it may be cached
+    Store(HttpRequest),
+    /// The service made the request conditional on entry `id`: a 304 is
+    /// answered from the cache, anything else handled as for `Store`
+    Revalidate { id: String, request: HttpRequest },
+}
+
+/// How to handle a request after consulting the cache.
+pub(super) enum CacheDecision {
+    /// Answer from the cache
+    Serve { id: String, entry: CacheEntry },
+    /// Send to the origin, as `conditional` if set
+    Fetch {
+        conditional: Option<HttpRequest>,
+        plan: CachePlan,
+    },
+}
+
+/// Payload of MSG_NET_CACHE_PURGE; no URL purges everything.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct PurgeRequest {
+    #[serde(default)]
+    url: Option<String>,
+}
+
+impl NetworkService {
+    pub(super) fn start_cache_load(&mut self) -> Result<(), AppError> {
+        self.start_vfs_mkdir(CACHE_DIR, VfsOp::PrepareCacheDir)?;
+        self.start_vfs_read(INDEX_PATH, VfsOp::LoadCacheIndex)
+    }
+
+    /// Complete the initial cache index load.
+    pub(super) fn finish_cache_load(
+        &mut self,
+        result: Result<Vec<u8>, String>,
+    ) -> Result<(), AppError> {
+        self.cache = match result {
+            Ok(data) => CacheIndex::from_json(&data).unwrap_or_else(|| {
+                syscall::debug(&format!(
+                    "NetworkService: {} is corrupt, starting with an empty cache",
+                    INDEX_PATH
+                ));
+                CacheIndex::default()
+            }),
+            // Missing on first boot
+            Err(_) => CacheIndex::default(),
+        };
+        self.cache_loaded = true;
+        self.stats_dirty = true;
+        syscall::debug(&format!(
+            "NetworkService: loaded cache index, {} entries ({} bytes)",
+            self.cache.entries.len(),
+            self.cache.total_bytes()
+        ));
+        self.replay_deferred()
+    }
+
+    /// Cache counters, with the current size of the cache.
+    pub(super) fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.cache.entries.len() as u64,
+            bytes: self.cache.total_bytes(),
+            ..self.cache_stats
+        }
+    }
+
+    /// Decide how the cache takes part in an allowed request.
+    pub(super) fn consult_cache(&mut self, request: &HttpRequest) -> CacheDecision {
+        if http_cache::invalidates(request.method) {
+            self.invalidate_url(&request.url);
+            return CacheDecision::Fetch {
+                conditional: None,
+                plan: CachePlan::Bypass,
+            };
+        }
+
+        let decision = match self.cache.lookup(request, syscall::get_wallclock()) {
+            CacheLookup::Bypass => {
+                return CacheDecision::Fetch {
+                    conditional: None,
+                    plan: CachePlan::Bypass,
+                }
+            }
+            CacheLookup::Fresh(id, entry) => {
+                return CacheDecision::Serve {
+                    id: id.into(),
+                    entry: entry.clone(),
+                }
+            }
+            CacheLookup::Miss => CacheDecision::Fetch {
+                conditional: None,
+                plan: CachePlan::Store(request.clone()),
+            },
+            CacheLookup::Revalidate(id, entry) => CacheDecision::Fetch {
+                conditional: Some(entry.conditional_request(request)),
+                plan: CachePlan::Revalidate {
+                    id: id.into(),
+                    request: request.clone(),
+                },
+            },
+        };
+        self.cache_stats.misses += 1;
+        self.stats_dirty = true;
+        decision
+    }
+
+    /// Answer a request with cached entry `id`.
+    pub(super) fn serve_cached(
+        &mut self,
+        client_pid: u32,
+        client_request_id: u32,
+        id: String,
+        entry: CacheEntry,
+        request: HttpRequest,
+    ) -> Result<(), AppError> {
+        if self.vfs_ops.len() >= MAX_VFS_OPS {
+            // Busy: the origin can answer instead
+            return self.refetch(client_pid, client_request_id, &request);
+        }
+        let path = body_path(&id);
+        let op = VfsOp::ServeCached {
+            client_pid,
+            client_request_id,
+            id,
+            entry,
+            request: request.clone(),
+        };
+        if let Err(e) = self.start_vfs_read(&path, op) {
+            syscall::debug(&format!(
+                "NetworkService: failed to read cached {}: {}",
+                path, e
+            ));
+            return self.refetch(client_pid, client_request_id, &request);
+        }
+        Ok(())
+    }
+
+    /// Send `request` to the origin unconditionally.
+    fn refetch(
+        &mut self,
+        client_pid: u32,
+        client_request_id: u32,
+        request: &HttpRequest,
+    ) -> Result<(), AppError> {
+        let request_json = serde_json::to_vec(request).unwrap_or_default();
+        self.start_fetch(
+            client_pid,
+            client_request_id,
+            &request_json,
+            CachePlan::Store(request.clone()),
+        )
+    }
+
+    /// Complete a cache hit once its body is read.
+    pub(super) fn finish_serve_cached(
+        &mut self,
+        client_pid: u32,
+        client_request_id: u32,
+        id: String,
+        entry: CacheEntry,
+        request: HttpRequest,
+        result: Result<Vec<u8>, String>,
+    ) -> Result<(), AppError> {
+        let body = match result {
+            Ok(body) if body.len() as u64 == entry.size => body,
+            Ok(body) => {
+                syscall::debug(&format!(
+                    "NetworkService: cached body {} is {} bytes, expected {}; refetching",
+                    id,
+                    body.len(),
+                    entry.size
+                ));
+                self.drop_entry(&id);
+                return self.refetch(client_pid, client_request_id, &request);
+            }
+            Err(e) => {
+                syscall::debug(&format!(
+                    "NetworkService: cached body {} unreadable ({}); refetching",
+                    id, e
+                ));
+                self.drop_entry(&id);
+                return self.refetch(client_pid, client_request_id, &request);
+            }
+        };
+
+        // Recency alone isn't worth a write; it's persisted with the next change
+        self.cache.touch(&id, syscall::get_wallclock());
+        self.cache_stats.hits += 1;
+        let response_json = serde_json::to_vec(&entry.response(body)).unwrap_or_default();
+        self.record_received(client_pid, response_json.len());
+        self.send_response(client_pid, client_request_id, &response_json)
+    }
+
+    /// Apply the cache plan of a successful fetch. Returns `true` if the
+    /// client has been answered from the cache instead.
+    pub(super) fn cache_result(
+        &mut self,
+        pending: &mut PendingRequest,
+        data: &[u8],
+    ) -> Result<bool, AppError> {
+        let (request, revalidating) =
+            match core::mem::replace(&mut pending.cache, CachePlan::Bypass) {
+                CachePlan::Bypass => return Ok(false),
+                CachePlan::Store(request) => (request, None),
+                CachePlan::Revalidate { id, request } => (request, Some(id)),
+            };
+        let Ok(HttpResponse {
+            result: Ok(success),
+        }) = serde_json::from_slice::<HttpResponse>(data)
+        else {
+            return Ok(false);
+        };
+        let now = syscall::get_wallclock();
+
+        if let Some(id) = revalidating.filter(|_| success.status == 304) {
+            let Some(entry) = self.cache.entries.get_mut(&id) else {
+                // Purged meanwhile, and the 304 means nothing to the client
+                self.refetch(pending.client_pid, pending.client_request_id, &request)?;
+                return Ok(true);
+            };
+            entry.refresh(&success, now);
+            let entry = entry.clone();
+            self.cache_dirty = true;
+            self.cache_stats.revalidated += 1;
+            self.stats_dirty = true;
+            self.serve_cached(
+                pending.client_pid,
+                pending.client_request_id,
+                id,
+                entry,
+                request,
+            )?;
+            return Ok(true);
+        }
+
+        self.store_response(&request, &success, now);
+        Ok(false)
+    }
+
+    /// Write `response` into the cache if it may be stored.
+    fn store_response(&mut self, request: &HttpRequest, response: &HttpSuccess, now: u64) {
+        let Some((id, entry)) = CacheEntry::from_response(request, response, now) else {
+            return;
+        };
+        if self.vfs_ops.len() >= MAX_VFS_OPS {
+            syscall::debug(&format!(
+                "NetworkService: VFS busy, not caching {}",
+                request.url
+            ));
+            return;
+        }
+        let path = body_path(&id);
+        let op = VfsOp::StoreCached {
+            id,
+            entry,
+            generation: self.cache_generation,
+        };
+        if let Err(e) = self.start_vfs_write(&path, &response.body, op) {
+            syscall::debug(&format!(
+                "NetworkService: failed to cache {}: {}",
+                request.url, e
+            ));
+        }
+    }
+
+    /// Add a stored response to the index once its body is written.
+    pub(super) fn finish_store_cached(
+        &mut self,
+        id: String,
+        entry: CacheEntry,
+        generation: u32,
+        result: Result<(), String>,
+    ) -> Result<(), AppError> {
+        if let Err(e) = result {
+            syscall::debug(&format!(
+                "NetworkService: VFS write failed for cached {}: {}",
+                entry.url, e
+            ));
+            return Ok(());
+        }
+        if generation != self.cache_generation {
+            // Purged while the write was in flight. A newer store of the same
+            // ID may lose its body too; serving it then refetches.
+            self.remove_bodies(alloc::vec![id]);
+            return Ok(());
+        }
+
+        let evicted = self.cache.insert(id, entry);
+        self.cache_stats.stored += 1;
+        self.cache_stats.evicted += evicted.len() as u64;
+        self.cache_dirty = true;
+        self.stats_dirty = true;
+        self.remove_bodies(evicted);
+        Ok(())
+    }
+
+    /// Drop every entry for `url`, after a request that may change it.
+    pub(super) fn invalidate_url(&mut self, url: &str) {
+        let ids = self.cache.remove_url(url);
+        if !ids.is_empty() {
+            self.cache_dirty = true;
+            self.stats_dirty = true;
+            self.remove_bodies(ids);
+        }
+    }
+
+    /// Drop entry `id` and its body.
+    fn drop_entry(&mut self, id: &str) {
+        if self.cache.entries.remove(id).is_some() {
+            self.cache_dirty = true;
+            self.stats_dirty = true;
+            self.remove_bodies(alloc::vec![String::from(id)]);
+        }
+    }
+
+    /// Delete the bodies of entries no longer in the index.
+    fn remove_bodies(&mut self, ids: Vec<String>) {
+        for id in ids {
+            if let Err(e) = self.start_vfs_unlink(&body_path(&id), VfsOp::RemoveCached) {
+                syscall::debug(&format!(
+                    "NetworkService: failed to delete cached body {}: {}",
+                    id, e
+                ));
+            }
+        }
+    }
+
+    /// Persist the index if it changed.
+    pub(super) fn persist_cache_index(&mut self) {
+        if !self.cache_dirty || !self.cache_loaded || self.vfs_ops.len() >= MAX_VFS_OPS {
+            return;
+        }
+        self.cache_dirty = false;
+        let value = self.cache.to_json();
+        if let Err(e) = self.start_vfs_write(INDEX_PATH, &value, VfsOp::PersistCacheIndex) {
+            self.cache_dirty = true;
+            syscall::debug(&format!(
+                "NetworkService: failed to persist cache index: {}",
+                e
+            ));
+        }
+    }
+
+    /// Handle MSG_NET_CACHE_PURGE
+    pub(super) fn handle_cache_purge(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = net::MSG_NET_CACHE_PURGE_RESPONSE;
+
+        if !self.check_policy_permission(msg.from_pid) {
+            return self.send_service_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: NET_CACHE_PURGE requires system privilege",
+            );
+        }
+        let request: PurgeRequest = if msg.data.is_empty() {
+            PurgeRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_service_error(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid purge request: expected {\"url\": string?}",
+                    );
+                }
+            }
+        };
+
+        let ids = match &request.url {
+            Some(url) => self.cache.remove_url(url),
+            None => self.cache.clear(),
+        };
+        syscall::debug(&format!(
+            "NetworkService: PID {} purged {} cached responses",
+            msg.from_pid,
+            ids.len()
+        ));
+        self.cache_generation = self.cache_generation.wrapping_add(1);
+        self.cache_dirty = true;
+        self.stats_dirty = true;
+        self.remove_bodies(ids);
+
+        let stats = serde_json::to_vec(&self.cache_stats()).unwrap_or_default();
+        self.send_service_response(msg.from_pid, &msg.cap_slots, tag, &stats)
+    }
+}
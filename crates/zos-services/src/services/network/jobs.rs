@@ -0,0 +1,400 @@
+//! Durable job handlers
+//!
+//! The journal is loaded from [`JOURNAL_PATH`] at startup, alongside the
+//! policy and cache index. A durable request is only sent once the journal
+//! entry for it is written, and a journal that can't be written fails the
+//! request instead. Jobs restored after a reload are sent again once
+//! everything is loaded, and their results are delivered from
+//! [`update`](zos_apps::ZeroApp::update) as their owners appear.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_network::cache as http_cache;
+use zos_network::jobs::{
+    result_path, JobAck, JobJournal, JobResult, JobState, JOBS_DIR, JOURNAL_PATH,
+};
+use zos_network::result as net_result;
+use zos_network::{HttpRequest, HttpResponse, NetworkError};
+use zos_process::net;
+
+use super::cache::CachePlan;
+use super::{NetworkService, PendingRequest, VfsOp, MAX_VFS_OPS};
+
+/// Minimum interval between scans for the owners of settled jobs.
+const JOB_SCAN_INTERVAL_MS: u64 = 1000;
+
+/// Name of process `pid`: the owner of the durable jobs it submits, and
+/// what services trusted for network access are known by.
+pub(super) fn process_name(pid: u32) -> Option<String> {
+    syscall::list_processes()
+        .into_iter()
+        .find(|p| p.pid == pid)
+        .map(|p| p.name)
+}
+
+impl NetworkService {
+    pub(super) fn start_jobs_load(&mut self) -> Result<(), AppError> {
+        self.start_vfs_mkdir(JOBS_DIR, VfsOp::PrepareJobsDir)?;
+        self.start_vfs_read(JOURNAL_PATH, VfsOp::LoadJobs)
+    }
+
+    /// Complete the initial journal load, settling the jobs a reload
+    /// interrupted.
+    pub(super) fn finish_jobs_load(
+        &mut self,
+        result: Result<Vec<u8>, String>,
+    ) -> Result<(), AppError> {
+        self.jobs = match result {
+            Ok(data) => JobJournal::from_json(&data).unwrap_or_else(|| {
+                syscall::debug(&format!(
+                    "NetworkService: {} is corrupt, durable jobs lost",
+                    JOURNAL_PATH
+                ));
+                JobJournal::default()
+            }),
+            // Missing on first boot
+            Err(_) => JobJournal::default(),
+        };
+        self.jobs_loaded = true;
+
+        let resend = self.jobs.restore();
+        syscall::debug(&format!(
+            "NetworkService: loaded {} durable jobs, resending {}",
+            self.jobs.jobs.len(),
+            resend.len()
+        ));
+        if !self.jobs.jobs.is_empty() {
+            self.persist_jobs(resend);
+        }
+        self.replay_deferred()
+    }
+
+    /// Write the journal, sending jobs `send` once it is written.
+    fn persist_jobs(&mut self, send: Vec<u32>) {
+        let value = self.jobs.to_json();
+        let op = VfsOp::CommitJobs { send: send.clone() };
+        if let Err(e) = self.start_vfs_write(JOURNAL_PATH, &value, op) {
+            syscall::debug(&format!(
+                "NetworkService: failed to persist durable jobs: {}",
+                e
+            ));
+            for id in send {
+                self.abandon_job(id, &e.to_string());
+            }
+        }
+    }
+
+    /// Complete a journal write.
+    pub(super) fn finish_jobs_commit(
+        &mut self,
+        send: Vec<u32>,
+        result: Result<(), String>,
+    ) -> Result<(), AppError> {
+        if let Err(e) = result {
+            // Rule 9: a job the journal doesn't record mustn't be sent
+            syscall::debug(&format!(
+                "NetworkService: VFS write failed for durable jobs: {}",
+                e
+            ));
+            for id in send {
+                self.abandon_job(id, &e);
+            }
+            return Ok(());
+        }
+        self.queued_jobs.extend(send);
+        self.send_queued_jobs()
+    }
+
+    /// Give up on job `id` because it couldn't be journaled.
+    fn abandon_job(&mut self, id: u32, error: &str) {
+        let error = NetworkError::Other(format!("Failed to record durable job: {}", error));
+        if let Some((client_pid, client_request_id)) = self.live_jobs.remove(&id) {
+            self.jobs.remove(id);
+            let _ = self.send_error_response(client_pid, client_request_id, &error);
+        } else if let Some(job) = self.jobs.get_mut(id) {
+            job.state = JobState::Failed(error);
+        }
+    }
+
+    /// Record a durable request and send it once it is journaled.
+    pub(super) fn submit_job(
+        &mut self,
+        client_pid: u32,
+        client_request_id: u32,
+        request: HttpRequest,
+    ) -> Result<(), AppError> {
+        let Some(owner) = process_name(client_pid) else {
+            return self.send_error_response(
+                client_pid,
+                client_request_id,
+                &NetworkError::Other("Durable requests need a named process".into()),
+            );
+        };
+        if self.vfs_ops.len() >= MAX_VFS_OPS {
+            return self.send_error_response(
+                client_pid,
+                client_request_id,
+                &NetworkError::Other("Service busy: too many pending VFS operations".into()),
+            );
+        }
+        if http_cache::invalidates(request.method) {
+            self.invalidate_url(&request.url);
+        }
+        let id = match self.jobs.add(&owner, request) {
+            Ok(id) => id,
+            Err(e) => return self.send_error_response(client_pid, client_request_id, &e),
+        };
+        syscall::debug(&format!(
+            "NetworkService: PID {} ({}) submitted durable job {}",
+            client_pid, owner, id
+        ));
+        self.live_jobs.insert(id, (client_pid, client_request_id));
+        self.persist_jobs(alloc::vec![id]);
+        Ok(())
+    }
+
+    /// Send the journaled jobs waiting on the service to be ready.
+    pub(super) fn send_queued_jobs(&mut self) -> Result<(), AppError> {
+        if !self.is_ready() {
+            return Ok(());
+        }
+        for id in core::mem::take(&mut self.queued_jobs) {
+            self.send_job(id)?;
+        }
+        Ok(())
+    }
+
+    /// Send job `id` to the origin, outside the cache.
+    fn send_job(&mut self, id: u32) -> Result<(), AppError> {
+        let Some(job) = self.jobs.get(id) else {
+            return Ok(());
+        };
+        let request = job.request.clone();
+        let live = self.live_jobs.get(&id).copied();
+        if live.is_none() {
+            // Submitted before the reload, so not yet checked against the
+            // policy in force now
+            if let Err(e) = self.policy.check(&request.url) {
+                return self.settle_job(id, HttpResponse::err(e));
+            }
+        }
+        let (client_pid, client_request_id) = live.unwrap_or((0, 0));
+
+        let request_json = serde_json::to_vec(&request).unwrap_or_default();
+        match syscall::network_fetch_async(&request_json) {
+            Ok(syscall_request_id) => {
+                self.pending_ops.insert(
+                    syscall_request_id as u32,
+                    PendingRequest {
+                        client_pid,
+                        client_request_id,
+                        cache: CachePlan::Bypass,
+                        job: Some(id),
+                    },
+                );
+                if live.is_some() {
+                    self.record_sent(client_pid, request_json.len());
+                }
+                Ok(())
+            }
+            Err(e) => {
+                let error = NetworkError::Other(format!(
+                    "Network syscall failed: SYS_NETWORK_FETCH returned {}",
+                    e
+                ));
+                self.finish_job(id, client_pid, client_request_id, HttpResponse::err(error))
+            }
+        }
+    }
+
+    /// Handle the MSG_NET_RESULT of job `id`.
+    pub(super) fn finish_job_fetch(
+        &mut self,
+        id: u32,
+        pending: PendingRequest,
+        result_type: u8,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let response = if result_type == net_result::NET_OK {
+            serde_json::from_slice(data).unwrap_or_else(|_| {
+                HttpResponse::err(NetworkError::Other("Malformed network result".into()))
+            })
+        } else if data.is_empty() {
+            HttpResponse::err(NetworkError::Other("Network error".into()))
+        } else {
+            HttpResponse::err(NetworkError::Other(
+                String::from_utf8_lossy(data).to_string(),
+            ))
+        };
+        if self.live_jobs.contains_key(&id) {
+            self.record_received(pending.client_pid, data.len());
+        }
+        self.finish_job(id, pending.client_pid, pending.client_request_id, response)
+    }
+
+    /// Complete job `id` with `response`: a job submitted since startup is
+    /// answered and dropped, a restored one settled for its owner.
+    pub(super) fn finish_job(
+        &mut self,
+        id: u32,
+        client_pid: u32,
+        client_request_id: u32,
+        response: HttpResponse,
+    ) -> Result<(), AppError> {
+        if self.live_jobs.remove(&id).is_none() {
+            return self.settle_job(id, response);
+        }
+        self.jobs.remove(id);
+        self.persist_jobs(Vec::new());
+        let response_json = serde_json::to_vec(&response).unwrap_or_default();
+        self.send_response(client_pid, client_request_id, &response_json)
+    }
+
+    /// Store the response to restored job `id` until its owner takes it.
+    fn settle_job(&mut self, id: u32, response: HttpResponse) -> Result<(), AppError> {
+        let response_json = serde_json::to_vec(&response).unwrap_or_default();
+        let path = result_path(id);
+        if let Err(e) = self.start_vfs_write(&path, &response_json, VfsOp::StoreJobResult { id }) {
+            return self.finish_store_job_result(id, Err(e.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Mark job `id` done once its response is written.
+    pub(super) fn finish_store_job_result(
+        &mut self,
+        id: u32,
+        result: Result<(), String>,
+    ) -> Result<(), AppError> {
+        let Some(job) = self.jobs.get_mut(id) else {
+            return Ok(());
+        };
+        job.state = match result {
+            Ok(()) => JobState::Done,
+            Err(e) => {
+                syscall::debug(&format!(
+                    "NetworkService: failed to store result of durable job {}: {}",
+                    id, e
+                ));
+                JobState::Failed(NetworkError::Other(format!("Result not stored: {}", e)))
+            }
+        };
+        self.persist_jobs(Vec::new());
+        self.last_job_scan_ms = 0;
+        Ok(())
+    }
+
+    /// Deliver settled jobs to their owners, at most once per process.
+    pub(super) fn deliver_job_results(&mut self) {
+        if !self.jobs_loaded {
+            return;
+        }
+        let settled: Vec<(u32, String, String, JobState)> = self
+            .jobs
+            .jobs
+            .iter()
+            .filter(|j| j.state != JobState::Pending)
+            .map(|j| (j.id, j.owner.clone(), j.name.clone(), j.state.clone()))
+            .collect();
+        if settled.is_empty() {
+            return;
+        }
+        let now = syscall::get_wallclock();
+        if now.saturating_sub(self.last_job_scan_ms) < JOB_SCAN_INTERVAL_MS {
+            return;
+        }
+        self.last_job_scan_ms = now;
+
+        let processes = syscall::list_processes();
+        for (id, owner, name, state) in settled {
+            let Some(pid) = processes.iter().find(|p| p.name == owner).map(|p| p.pid) else {
+                continue;
+            };
+            if self.delivered_jobs.get(&id) == Some(&pid) || self.vfs_ops.len() >= MAX_VFS_OPS {
+                continue;
+            }
+            self.delivered_jobs.insert(id, pid);
+            match state {
+                JobState::Failed(e) => self.send_job_result(pid, name, HttpResponse::err(e)),
+                _ => {
+                    if let Err(e) =
+                        self.start_vfs_read(&result_path(id), VfsOp::ReadJobResult { id, pid })
+                    {
+                        self.delivered_jobs.remove(&id);
+                        syscall::debug(&format!(
+                            "NetworkService: failed to read result of durable job {}: {}",
+                            id, e
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Deliver job `id` to `pid` once its response is read.
+    pub(super) fn finish_read_job_result(
+        &mut self,
+        id: u32,
+        pid: u32,
+        result: Result<Vec<u8>, String>,
+    ) -> Result<(), AppError> {
+        let Some(name) = self.jobs.get(id).map(|j| j.name.clone()) else {
+            return Ok(());
+        };
+        let response = match result.map(|data| serde_json::from_slice::<HttpResponse>(&data)) {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => HttpResponse::err(NetworkError::Other("Stored result corrupt".into())),
+            Err(e) => HttpResponse::err(NetworkError::Other(format!("Result lost: {}", e))),
+        };
+        self.send_job_result(pid, name, response);
+        Ok(())
+    }
+
+    fn send_job_result(&self, pid: u32, job: String, response: HttpResponse) {
+        syscall::debug(&format!(
+            "NetworkService: delivering durable job '{}' to PID {}",
+            job, pid
+        ));
+        let json = serde_json::to_vec(&JobResult { job, response }).unwrap_or_default();
+        self.send_to_client(pid, net::MSG_NET_JOB_RESULT, &json);
+    }
+
+    /// Handle MSG_NET_JOB_ACK: the owner has the result, so forget the job.
+    pub(super) fn handle_job_ack(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Ok(ack) = serde_json::from_slice::<JobAck>(&msg.data) else {
+            syscall::debug("NetworkService: malformed MSG_NET_JOB_ACK");
+            return Ok(());
+        };
+        let Some(owner) = process_name(msg.from_pid) else {
+            return Ok(());
+        };
+        let Some(job) = self.jobs.find(&owner, &ack.job) else {
+            return Ok(());
+        };
+        if job.state == JobState::Pending {
+            syscall::debug(&format!(
+                "NetworkService: PID {} acknowledged durable job '{}' before it finished",
+                msg.from_pid, ack.job
+            ));
+            return Ok(());
+        }
+
+        let id = job.id;
+        let had_result = job.state == JobState::Done;
+        self.jobs.remove(id);
+        self.delivered_jobs.remove(&id);
+        if had_result {
+            if let Err(e) = self.start_vfs_unlink(&result_path(id), VfsOp::RemoveJobResult) {
+                syscall::debug(&format!(
+                    "NetworkService: failed to delete result of durable job {}: {}",
+                    id, e
+                ));
+            }
+        }
+        self.persist_jobs(Vec::new());
+        Ok(())
+    }
+}
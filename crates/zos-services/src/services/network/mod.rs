@@ -4,19 +4,43 @@
 //! - Handles MSG_NET_REQUEST IPC messages from processes
 //! - Performs HTTP fetch operations via async syscalls (routed through supervisor)
 //! - Responds with MSG_NET_RESPONSE messages
+//! - Enforces the system-wide [`NetworkPolicy`] (offline mode, origin
+//!   allow/deny lists) persisted at `/system/config/network.json`
+//! - Counts each client's traffic for the supervisor's metrics
+//! - Caches GET responses under `/var/cache/net`, honoring their
+//!   Cache-Control and revalidating with ETag/Last-Modified
+//! - Journals durable requests under `/var/lib/net/jobs` so they complete
+//!   across a page reload
 //!
 //! # Safety Invariants
 //!
 //! **Success means:**
 //! - HTTP request completed (success or HTTP error) AND response delivered to client
 //! - Request-response correlation maintained via syscall_request_id
+//! - POLICY_SET: Change validated AND written to VFS AND in-memory policy updated
+//! - Cache hit: stored body read back at its recorded size AND delivered
+//! - Durable request: journaled BEFORE it is sent, dropped once answered
 //!
 //! **Acceptable partial failure:**
 //! - Network timeout → error response to client
 //! - HTTP error status → forwarded to client as-is
+//! - Policy file missing at startup → allow everything
+//! - Policy file corrupt at startup → offline until the policy is set again
+//! - Cache index missing or corrupt at startup → start with an empty cache
+//! - Cache write fails → response delivered, just not cached
+//! - Cached body unreadable → entry dropped, request fetched from the origin
+//! - Journal missing at startup → no durable jobs; corrupt → jobs lost
+//! - Reload after a durable job is answered but before the journal write →
+//!   the job runs again and its result is delivered a second time
 //!
 //! **Forbidden:**
 //! - Allowing unauthorized processes to make network requests
+//! - Forwarding a request before the policy is loaded, or one it refuses
+//! - Serving a cached response to a request that bypasses the cache, or a
+//!   304 from a revalidation the service added to a client
+//! - Acknowledging a POLICY_SET before the policy is persisted
+//! - Sending a durable request the journal doesn't record, or resending an
+//!   interrupted POST/PATCH
 //! - Unbounded pending operations (DoS vector)
 //! - Orphan pending ops (client response never sent)
 //! - Mismatched request-response correlation
@@ -73,18 +97,64 @@
 //! - `MSG_NET_REQUEST (0x9000)`: HTTP request
 //! - `MSG_NET_RESPONSE (0x9001)`: HTTP response
 //! - `MSG_NET_RESULT (0x9002)`: Internal result from HAL
+//! - `MSG_NET_POLICY_GET (0x9010)`: Current policy
+//! - `MSG_NET_POLICY_SET (0x9012)`: Change the policy (Settings)
+//! - `MSG_NET_STATS (0x9014)`: Per-process traffic and cache counters
+//! - `MSG_NET_CACHE_PURGE (0x9016)`: Drop cached responses (Settings)
+//! - `MSG_NET_JOB_RESULT (0x9018)`: Durable job result after a reload
+//! - `MSG_NET_JOB_ACK (0x9019)`: Durable job result received
+//!
+//! # Policy
+//!
+//! Requests are checked in order: offline mode fails everything with
+//! [`NetworkError::Offline`], then denied origins and origins missing from
+//! a non-empty allow list fail with [`NetworkError::PolicyDenied`]. Going
+//! offline also fails the requests already in flight. Processes that call
+//! SYS_NETWORK_FETCH directly rather than going through this service are
+//! not covered.
+//!
+//! # Cache
+//!
+//! Allowed requests are then looked up in the [`CacheIndex`] (see
+//! [`zos_network::cache`] for what is stored and for how long). A fresh
+//! entry is answered from its body file without contacting the origin; a
+//! stale one is sent on as a conditional request, and a 304 is answered
+//! from the cache. Bodies are written after the response is delivered, and
+//! the index is persisted at most once per update tick.
+//!
+//! # Durable Jobs
+//!
+//! A request with `durable` set skips the cache and is recorded in the
+//! [`JobJournal`](zos_network::jobs::JobJournal) under its owner's process
+//! name before it is sent. Answered before any reload, it is delivered as
+//! MSG_NET_RESPONSE like any other. After a reload the interrupted jobs are
+//! resent or failed (see [`zos_network::jobs`]), and the outcome is sent as
+//! MSG_NET_JOB_RESULT to each new process with the owner's name until one
+//! acknowledges it.
 
 extern crate alloc;
 
+mod cache;
+mod jobs;
+mod policy;
+mod stats;
+
 use alloc::collections::BTreeMap;
 use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
 use crate::manifests::NETWORK_MANIFEST;
+use cache::{CacheDecision, CachePlan};
 use zos_apps::syscall;
 use zos_apps::{AppContext, AppError, AppManifest, ControlFlow, Message, ZeroApp};
+use zos_network::cache::{CacheEntry, CacheIndex, CacheStats};
+use zos_network::jobs::JobJournal;
+use zos_network::policy::{AppTraffic, NetworkPolicy};
 use zos_network::result as net_result;
+use zos_network::{HttpRequest, HttpResponse, NetworkError};
 use zos_process::net;
+use zos_vfs::async_client;
+use zos_vfs::ipc::vfs_msg;
 
 // =============================================================================
 // Permission & Limit Constants
@@ -93,12 +163,28 @@ use zos_process::net;
 /// Maximum number of pending network operations (DoS protection per Rule 11)
 const MAX_PENDING_OPS: usize = 64;
 
+/// Maximum pending VFS operations started for policy changes and cache
+/// reads and writes (Rule 11). Deleting cached bodies is bounded by the
+/// cache size instead.
+const MAX_VFS_OPS: usize = 64;
+
+/// Maximum messages buffered while the policy is loading (Rule 11)
+const MAX_DEFERRED_MESSAGES: usize = 16;
+
+/// Maximum processes with traffic counters (Rule 11)
+const MAX_TRACKED_APPS: usize = 128;
+
 /// System service PIDs that are always allowed network access.
 /// - PID 0: Supervisor
 /// - PID 1: Init
 /// - PID 7: Identity Service (needs network for auth flows)
 const TRUSTED_PIDS_FOR_NETWORK: &[u32] = &[0, 1, 7];
 
+/// System services allowed network access by name, as their PIDs vary
+/// with boot order.
+/// - messaging: posts and reads sealed envelopes on relays
+const TRUSTED_SERVICES_FOR_NETWORK: &[&str] = &["messaging"];
+
 // =============================================================================
 // Pending Network Operations
 // =============================================================================
@@ -110,6 +196,67 @@ struct PendingRequest {
     client_pid: u32,
     /// Original client request ID (from NetRequest)
     client_request_id: u32,
+    /// What to do with the response in the cache
+    cache: CachePlan,
+    /// Durable job the request runs, if any
+    job: Option<u32>,
+}
+
+/// Pending VFS operations. VFS responses carry no request ID, so these are
+/// matched oldest-first by [`OpType`].
+#[derive(Clone, Debug)]
+enum VfsOp {
+    /// Initial load of the policy
+    LoadPolicy,
+    /// Persist a policy change
+    CommitPolicy {
+        client_pid: u32,
+        cap_slots: Vec<u32>,
+        policy: NetworkPolicy,
+    },
+    /// Ensure the cache directory exists
+    PrepareCacheDir,
+    /// Initial load of the cache index
+    LoadCacheIndex,
+    /// Read a cached body to answer a request
+    ServeCached {
+        client_pid: u32,
+        client_request_id: u32,
+        id: String,
+        entry: CacheEntry,
+        request: HttpRequest,
+    },
+    /// Write a response body into the cache
+    StoreCached {
+        id: String,
+        entry: CacheEntry,
+        generation: u32,
+    },
+    /// Persist the cache index
+    PersistCacheIndex,
+    /// Delete an evicted or purged body
+    RemoveCached,
+    /// Ensure the durable jobs directory exists
+    PrepareJobsDir,
+    /// Initial load of the job journal
+    LoadJobs,
+    /// Persist the journal, then send jobs `send`
+    CommitJobs { send: Vec<u32> },
+    /// Write the response to a restored job
+    StoreJobResult { id: u32 },
+    /// Read a job's response to deliver it to `pid`
+    ReadJobResult { id: u32, pid: u32 },
+    /// Delete an acknowledged job's response
+    RemoveJobResult,
+}
+
+/// Operation type for matching VFS responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OpType {
+    Read,
+    Write,
+    Mkdir,
+    Unlink,
 }
 
 // =============================================================================
@@ -124,6 +271,42 @@ pub struct NetworkService {
     pending_ops: BTreeMap<u32, PendingRequest>,
     /// Next client request ID (for internal tracking)
     next_request_id: u32,
+    /// Network policy (valid once `policy_loaded`)
+    policy: NetworkPolicy,
+    /// Whether the policy has been loaded from VFS
+    policy_loaded: bool,
+    /// Pending VFS operations: request_id -> (operation, op_type)
+    vfs_ops: BTreeMap<u32, (VfsOp, OpType)>,
+    /// Next request ID for VFS correlation
+    next_vfs_request_id: u32,
+    /// Requests received before the policy and cache index finished loading
+    deferred: Vec<Message>,
+    /// Traffic counters by client PID
+    traffic: BTreeMap<u32, AppTraffic>,
+    /// Whether the counters changed since they were last published
+    stats_dirty: bool,
+    /// Metadata of cached responses
+    cache: CacheIndex,
+    /// Whether the cache index has been loaded from VFS
+    cache_loaded: bool,
+    /// Whether the cache index changed since it was last persisted
+    cache_dirty: bool,
+    /// Bumped on every purge, so stores started before it are discarded
+    cache_generation: u32,
+    /// Cache counters (entries and bytes are filled in from the index)
+    cache_stats: CacheStats,
+    /// Durable jobs
+    jobs: JobJournal,
+    /// Whether the job journal has been loaded from VFS
+    jobs_loaded: bool,
+    /// Jobs submitted since startup: job ID -> (client PID, client request ID)
+    live_jobs: BTreeMap<u32, (u32, u32)>,
+    /// Journaled jobs to send once the service is ready
+    queued_jobs: Vec<u32>,
+    /// Settled jobs already delivered: job ID -> PID of the owner process
+    delivered_jobs: BTreeMap<u32, u32>,
+    /// Wall-clock ms of the last scan for job owners
+    last_job_scan_ms: u64,
 }
 
 impl Default for NetworkService {
@@ -132,6 +315,24 @@ impl Default for NetworkService {
             registered: false,
             pending_ops: BTreeMap::new(),
             next_request_id: 1,
+            policy: NetworkPolicy::default(),
+            policy_loaded: false,
+            vfs_ops: BTreeMap::new(),
+            next_vfs_request_id: 0,
+            deferred: Vec::new(),
+            traffic: BTreeMap::new(),
+            stats_dirty: false,
+            cache: CacheIndex::default(),
+            cache_loaded: false,
+            cache_dirty: false,
+            cache_generation: 0,
+            cache_stats: CacheStats::default(),
+            jobs: JobJournal::default(),
+            jobs_loaded: false,
+            live_jobs: BTreeMap::new(),
+            queued_jobs: Vec::new(),
+            delivered_jobs: BTreeMap::new(),
+            last_job_scan_ms: 0,
         }
     }
 }
@@ -139,7 +340,9 @@ impl Default for NetworkService {
 impl NetworkService {
     /// Check if caller is authorized to make network requests (Rule 4: fail-closed).
     fn check_network_permission(&self, from_pid: u32) -> bool {
-        let allowed = TRUSTED_PIDS_FOR_NETWORK.contains(&from_pid);
+        let allowed = TRUSTED_PIDS_FOR_NETWORK.contains(&from_pid)
+            || jobs::process_name(from_pid)
+                .is_some_and(|name| TRUSTED_SERVICES_FOR_NETWORK.contains(&name.as_str()));
         if !allowed {
             syscall::debug(&format!(
                 "NetworkService: SECURITY - Network request denied for PID {} (not in trusted list)",
@@ -164,8 +367,87 @@ impl NetworkService {
         }
     }
 
+    /// Allocate the ID a client's response is tagged with.
+    fn alloc_client_request_id(&mut self) -> u32 {
+        let id = self.next_request_id;
+        self.next_request_id += 1;
+        id
+    }
+
+    /// Whether the policy, cache index and job journal are loaded, so
+    /// requests can be handled.
+    fn is_ready(&self) -> bool {
+        self.policy_loaded && self.cache_loaded && self.jobs_loaded
+    }
+
+    // =========================================================================
+    // VFS helpers (async, non-blocking)
+    // =========================================================================
+
+    fn alloc_vfs_request_id(&mut self) -> u32 {
+        self.next_vfs_request_id = self.next_vfs_request_id.wrapping_add(1).max(1);
+        self.next_vfs_request_id
+    }
+
+    /// Find and remove the oldest pending VFS operation of the given type.
+    fn take_vfs_op(&mut self, op_type: OpType) -> Option<VfsOp> {
+        let request_id = self
+            .vfs_ops
+            .iter()
+            .find(|(_, (_, t))| *t == op_type)
+            .map(|(id, _)| *id)?;
+        self.vfs_ops.remove(&request_id).map(|(op, _)| op)
+    }
+
+    fn start_vfs_read(&mut self, path: &str, op: VfsOp) -> Result<(), AppError> {
+        let request_id = self.alloc_vfs_request_id();
+        async_client::send_read_request(path)?;
+        self.vfs_ops.insert(request_id, (op, OpType::Read));
+        Ok(())
+    }
+
+    fn start_vfs_write(&mut self, path: &str, value: &[u8], op: VfsOp) -> Result<(), AppError> {
+        let request_id = self.alloc_vfs_request_id();
+        async_client::send_write_request(path, value)?;
+        self.vfs_ops.insert(request_id, (op, OpType::Write));
+        Ok(())
+    }
+
+    fn start_vfs_mkdir(&mut self, path: &str, op: VfsOp) -> Result<(), AppError> {
+        let request_id = self.alloc_vfs_request_id();
+        async_client::send_mkdir_request(path, true)?;
+        self.vfs_ops.insert(request_id, (op, OpType::Mkdir));
+        Ok(())
+    }
+
+    fn start_vfs_unlink(&mut self, path: &str, op: VfsOp) -> Result<(), AppError> {
+        let request_id = self.alloc_vfs_request_id();
+        async_client::send_unlink_request(path)?;
+        self.vfs_ops.insert(request_id, (op, OpType::Unlink));
+        Ok(())
+    }
+
+    /// Check a request against the policy, counting refusals.
+    fn check_policy(&mut self, from_pid: u32, request: &HttpRequest) -> Result<(), NetworkError> {
+        match self.policy.check(&request.url) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                if matches!(e, NetworkError::Offline | NetworkError::PolicyDenied) {
+                    syscall::debug(&format!(
+                        "NetworkService: {} request from PID {} refused: {}",
+                        request.method.as_str(),
+                        from_pid,
+                        e.message()
+                    ));
+                    self.record_denied(from_pid);
+                }
+                Err(e)
+            }
+        }
+    }
+
     /// Handle MSG_NET_REQUEST - perform HTTP fetch
-    fn handle_net_request(&mut self, _ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
+    fn handle_net_request(&mut self, msg: &Message) -> Result<(), AppError> {
         // Parse the request
         let request_json = &msg.data;
 
@@ -176,15 +458,16 @@ impl NetworkService {
         ));
 
         // Extract client request ID early for error responses
-        let client_request_id = self.next_request_id;
-        self.next_request_id += 1;
+        let client_request_id = self.alloc_client_request_id();
 
         // Permission check (Rule 4: fail-closed)
         if !self.check_network_permission(msg.from_pid) {
             return self.send_error_response(
                 msg.from_pid,
                 client_request_id,
-                "Permission denied: MSG_NET_REQUEST requires network capability",
+                &NetworkError::Other(
+                    "Permission denied: MSG_NET_REQUEST requires network capability".into(),
+                ),
             );
         }
 
@@ -193,11 +476,55 @@ impl NetworkService {
             return self.send_error_response(
                 msg.from_pid,
                 client_request_id,
-                "Service busy: pending network operation limit reached",
+                &NetworkError::Other(
+                    "Service busy: pending network operation limit reached".into(),
+                ),
             );
         }
 
-        // Start async network fetch via syscall
+        let request: HttpRequest = match serde_json::from_slice(request_json) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    client_request_id,
+                    &NetworkError::Other("Invalid request: expected HttpRequest JSON".into()),
+                );
+            }
+        };
+        if let Err(e) = self.check_policy(msg.from_pid, &request) {
+            return self.send_error_response(msg.from_pid, client_request_id, &e);
+        }
+        if request.durable.is_some() {
+            return self.submit_job(msg.from_pid, client_request_id, request);
+        }
+
+        match self.consult_cache(&request) {
+            CacheDecision::Serve { id, entry } => {
+                self.serve_cached(msg.from_pid, client_request_id, id, entry, request)
+            }
+            CacheDecision::Fetch {
+                conditional: Some(conditional),
+                plan,
+            } => {
+                let conditional_json = serde_json::to_vec(&conditional).unwrap_or_default();
+                self.start_fetch(msg.from_pid, client_request_id, &conditional_json, plan)
+            }
+            CacheDecision::Fetch {
+                conditional: None,
+                plan,
+            } => self.start_fetch(msg.from_pid, client_request_id, request_json, plan),
+        }
+    }
+
+    /// Send a request on to the network via syscall.
+    fn start_fetch(
+        &mut self,
+        client_pid: u32,
+        client_request_id: u32,
+        request_json: &[u8],
+        cache: CachePlan,
+    ) -> Result<(), AppError> {
         match syscall::network_fetch_async(request_json) {
             Ok(syscall_request_id) => {
                 let syscall_request_id = syscall_request_id as u32;
@@ -210,10 +537,13 @@ impl NetworkService {
                 self.pending_ops.insert(
                     syscall_request_id,
                     PendingRequest {
-                        client_pid: msg.from_pid,
+                        client_pid,
                         client_request_id,
+                        cache,
+                        job: None,
                     },
                 );
+                self.record_sent(client_pid, request_json.len());
 
                 Ok(())
             }
@@ -224,16 +554,19 @@ impl NetworkService {
                 ));
                 // Send error response with context (Rule 9)
                 self.send_error_response(
-                    msg.from_pid,
+                    client_pid,
                     client_request_id,
-                    &format!("Network syscall failed: SYS_NETWORK_FETCH returned {}", e),
+                    &NetworkError::Other(format!(
+                        "Network syscall failed: SYS_NETWORK_FETCH returned {}",
+                        e
+                    )),
                 )
             }
         }
     }
 
     /// Handle MSG_NET_RESULT - async network operation completed
-    fn handle_net_result(&mut self, _ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
+    fn handle_net_result(&mut self, msg: &Message) -> Result<(), AppError> {
         // Parse network result
         // Format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
         if msg.data.len() < 9 {
@@ -260,7 +593,7 @@ impl NetworkService {
         ));
 
         // Look up pending operation
-        let pending = match self.pending_ops.remove(&request_id) {
+        let mut pending = match self.pending_ops.remove(&request_id) {
             Some(p) => p,
             None => {
                 syscall::debug(&format!(
@@ -271,6 +604,15 @@ impl NetworkService {
             }
         };
 
+        if let Some(id) = pending.job {
+            return self.finish_job_fetch(id, pending, result_type, data);
+        }
+        if result_type == net_result::NET_OK && self.cache_result(&mut pending, data)? {
+            // Not modified: answered from the cache instead
+            return Ok(());
+        }
+        self.record_received(pending.client_pid, data.len());
+
         // Forward result to client
         if result_type == net_result::NET_OK {
             // Success - forward the response data
@@ -282,7 +624,11 @@ impl NetworkService {
             } else {
                 "Network error".into()
             };
-            self.send_error_response(pending.client_pid, pending.client_request_id, &error_msg)
+            self.send_error_response(
+                pending.client_pid,
+                pending.client_request_id,
+                &NetworkError::Other(error_msg),
+            )
         }
     }
 
@@ -298,17 +644,21 @@ impl NetworkService {
         let mut data = Vec::with_capacity(4 + response_data.len());
         data.extend_from_slice(&request_id.to_le_bytes());
         data.extend_from_slice(response_data);
+        self.send_to_client(to_pid, net::MSG_NET_RESPONSE, &data);
+        Ok(())
+    }
 
-        // Send via debug message for supervisor to route via IPC
+    /// Send a message to a client via debug message for the supervisor to
+    /// route via IPC.
+    fn send_to_client(&self, to_pid: u32, tag: u32, data: &[u8]) {
         let hex: String = data.iter().map(|b| format!("{:02x}", b)).collect();
         syscall::debug(&format!(
-            "NET:RESPONSE:{}:{:08x}:{}",
+            "{}{}:{:08x}:{}",
+            zos_ipc::debug::NET_RESPONSE,
             to_pid,
-            net::MSG_NET_RESPONSE,
+            tag,
             hex
         ));
-
-        Ok(())
     }
 
     /// Send error response to client
@@ -316,29 +666,166 @@ impl NetworkService {
         &self,
         to_pid: u32,
         request_id: u32,
-        error_msg: &str,
+        error: &NetworkError,
     ) -> Result<(), AppError> {
-        // Build error response JSON
-        let error_json = format!(
-            r#"{{"result":{{"Err":{{"Other":"{}"}}}}}}"#,
-            error_msg.replace('"', "\\\"")
-        );
-        let error_bytes = error_json.as_bytes();
+        let error_json =
+            serde_json::to_vec(&HttpResponse::err(error.clone())).unwrap_or_default();
+        self.send_response(to_pid, request_id, &error_json)
+    }
 
-        // Build response message
-        let mut data = Vec::with_capacity(4 + error_bytes.len());
-        data.extend_from_slice(&request_id.to_le_bytes());
-        data.extend_from_slice(error_bytes);
+    /// Send a JSON response to a policy or stats request via reply cap,
+    /// falling back to the debug channel.
+    fn send_service_response(
+        &self,
+        to_pid: u32,
+        cap_slots: &[u32],
+        tag: u32,
+        json: &[u8],
+    ) -> Result<(), AppError> {
+        if let Some(&reply_slot) = cap_slots.first() {
+            match syscall::send(reply_slot, tag, json) {
+                Ok(()) => return Ok(()),
+                Err(e) => syscall::debug(&format!(
+                    "NetworkService: Reply cap send failed ({}), falling back to debug channel",
+                    e
+                )),
+            }
+        }
 
-        // Send via debug message for supervisor to route via IPC
-        let hex: String = data.iter().map(|b| format!("{:02x}", b)).collect();
-        syscall::debug(&format!(
-            "NET:RESPONSE:{}:{:08x}:{}",
-            to_pid,
-            net::MSG_NET_RESPONSE,
-            hex
-        ));
+        let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+        syscall::debug(&format!("SERVICE:RESPONSE:{}:{:08x}:{}", to_pid, tag, hex));
+        Ok(())
+    }
 
+    /// Send `{"error": ...}` to a policy or stats request
+    fn send_service_error(
+        &self,
+        to_pid: u32,
+        cap_slots: &[u32],
+        tag: u32,
+        error: &str,
+    ) -> Result<(), AppError> {
+        let json = format!(r#"{{"error":"{}"}}"#, error.replace('"', "\\\""));
+        self.send_service_response(to_pid, cap_slots, tag, json.as_bytes())
+    }
+
+    fn dispatch_request(&mut self, msg: Message) -> Result<(), AppError> {
+        if !self.is_ready() {
+            return self.defer(msg);
+        }
+        match msg.tag {
+            net::MSG_NET_REQUEST => self.handle_net_request(&msg),
+            net::MSG_NET_POLICY_GET => self.handle_policy_get(&msg),
+            net::MSG_NET_POLICY_SET => self.handle_policy_set(&msg),
+            net::MSG_NET_STATS => self.handle_stats(&msg),
+            net::MSG_NET_CACHE_PURGE => self.handle_cache_purge(&msg),
+            net::MSG_NET_JOB_ACK => self.handle_job_ack(&msg),
+            _ => Ok(()),
+        }
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Handle VFS read response (MSG_VFS_READ_RESPONSE)
+    fn handle_vfs_read_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Some(op) = self.take_vfs_op(OpType::Read) else {
+            syscall::debug("NetworkService: VFS read response but no pending read");
+            return Ok(());
+        };
+        let result = async_client::parse_read_response(&msg.data);
+
+        match op {
+            VfsOp::LoadPolicy => self.finish_policy_load(result),
+            VfsOp::LoadCacheIndex => self.finish_cache_load(result),
+            VfsOp::LoadJobs => self.finish_jobs_load(result),
+            VfsOp::ReadJobResult { id, pid } => self.finish_read_job_result(id, pid, result),
+            VfsOp::ServeCached {
+                client_pid,
+                client_request_id,
+                id,
+                entry,
+                request,
+            } => self.finish_serve_cached(client_pid, client_request_id, id, entry, request, result),
+            other => {
+                syscall::debug(&format!(
+                    "NetworkService: unexpected read response for {:?}",
+                    other
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    /// Handle VFS write response (MSG_VFS_WRITE_RESPONSE)
+    fn handle_vfs_write_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Some(op) = self.take_vfs_op(OpType::Write) else {
+            syscall::debug("NetworkService: VFS write response but no pending write");
+            return Ok(());
+        };
+        let result = async_client::parse_write_response(&msg.data);
+
+        match op {
+            VfsOp::CommitPolicy {
+                client_pid,
+                cap_slots,
+                policy,
+            } => self.finish_policy_commit(client_pid, &cap_slots, policy, result),
+            VfsOp::StoreCached {
+                id,
+                entry,
+                generation,
+            } => self.finish_store_cached(id, entry, generation, result),
+            VfsOp::CommitJobs { send } => self.finish_jobs_commit(send, result),
+            VfsOp::StoreJobResult { id } => self.finish_store_job_result(id, result),
+            VfsOp::PersistCacheIndex => {
+                if let Err(e) = result {
+                    // Rule 9: entries stored since the last good write are
+                    // forgotten on restart, leaving their bodies orphaned
+                    self.cache_dirty = true;
+                    syscall::debug(&format!(
+                        "NetworkService: VFS write failed for cache index: {}",
+                        e
+                    ));
+                }
+                Ok(())
+            }
+            other => {
+                syscall::debug(&format!(
+                    "NetworkService: unexpected write response for {:?}",
+                    other
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    /// Handle VFS mkdir response (MSG_VFS_MKDIR_RESPONSE)
+    fn handle_vfs_mkdir_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        if self.take_vfs_op(OpType::Mkdir).is_none() {
+            return Ok(());
+        }
+        // Already-existing directories report an error here; a genuinely
+        // missing directory surfaces as failed cache writes.
+        if let Err(e) = async_client::parse_mkdir_response(&msg.data) {
+            syscall::debug(&format!("NetworkService: mkdir: {}", e));
+        }
+        Ok(())
+    }
+
+    /// Handle VFS unlink response (MSG_VFS_UNLINK_RESPONSE)
+    fn handle_vfs_unlink_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Some(op) = self.take_vfs_op(OpType::Unlink) else {
+            return Ok(());
+        };
+        if let Err(e) = async_client::parse_unlink_response(&msg.data) {
+            let what = match op {
+                VfsOp::RemoveJobResult => "durable job result",
+                _ => "cached body",
+            };
+            syscall::debug(&format!("NetworkService: failed to delete {}: {}", what, e));
+        }
         Ok(())
     }
 }
@@ -369,23 +856,41 @@ impl ZeroApp for NetworkService {
         self.registered = true;
 
         syscall::debug("NetworkService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
 
-        Ok(())
+        self.start_policy_load()?;
+        self.start_cache_load()?;
+        self.start_jobs_load()
     }
 
     fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        self.persist_cache_index();
+        self.deliver_job_results();
+        self.publish_stats();
         ControlFlow::Yield
     }
 
-    fn on_message(&mut self, ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+    fn on_message(&mut self, _ctx: &AppContext, msg: Message) -> Result<(), AppError> {
         syscall::debug(&format!(
             "NetworkService: Received message tag 0x{:x} from PID {}",
             msg.tag, msg.from_pid
         ));
 
         match msg.tag {
-            net::MSG_NET_REQUEST => self.handle_net_request(ctx, &msg),
-            net::MSG_NET_RESULT => self.handle_net_result(ctx, &msg),
+            net::MSG_NET_RESULT => self.handle_net_result(&msg),
+
+            // Policy and cache persistence (Invariant 31 compliant - storage via VFS IPC)
+            vfs_msg::MSG_VFS_READ_RESPONSE => self.handle_vfs_read_response(&msg),
+            vfs_msg::MSG_VFS_WRITE_RESPONSE => self.handle_vfs_write_response(&msg),
+            vfs_msg::MSG_VFS_MKDIR_RESPONSE => self.handle_vfs_mkdir_response(&msg),
+            vfs_msg::MSG_VFS_UNLINK_RESPONSE => self.handle_vfs_unlink_response(&msg),
+
+            net::MSG_NET_REQUEST
+            | net::MSG_NET_POLICY_GET
+            | net::MSG_NET_POLICY_SET
+            | net::MSG_NET_STATS
+            | net::MSG_NET_CACHE_PURGE
+            | net::MSG_NET_JOB_ACK => self.dispatch_request(msg),
             _ => {
                 syscall::debug(&format!(
                     "NetworkService: Unknown message tag 0x{:x}",
@@ -408,6 +913,8 @@ impl ZeroApp for NetworkService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::mock_message;
+    use zos_network::jobs::JobState;
 
     // -------------------------------------------------------------------------
     // Permission check tests (Rule 4: fail-closed)
@@ -456,6 +963,8 @@ mod tests {
                 PendingRequest {
                     client_pid: 1,
                     client_request_id: i as u32,
+                    cache: CachePlan::Bypass,
+                    job: None,
                 },
             );
         }
@@ -475,4 +984,372 @@ mod tests {
         assert_eq!(id1, 1);
         assert_eq!(id2, 2);
     }
+
+    // -------------------------------------------------------------------------
+    // Policy tests
+    // -------------------------------------------------------------------------
+
+    fn loaded_service() -> NetworkService {
+        NetworkService {
+            policy_loaded: true,
+            cache_loaded: true,
+            jobs_loaded: true,
+            ..Default::default()
+        }
+    }
+
+    fn update(json: &str) -> policy::PolicyUpdate {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_policy_permission() {
+        let service = NetworkService::default();
+        for &pid in policy::TRUSTED_PIDS_FOR_POLICY {
+            assert!(service.check_policy_permission(pid));
+        }
+        assert!(!service.check_policy_permission(7));
+    }
+
+    #[test]
+    fn test_apply_update_keeps_absent_fields() {
+        let mut service = loaded_service();
+        service.policy.deny = alloc::vec!["https://ads.example.com".into()];
+
+        let next = service.apply_update(update(r#"{"offline":true}"#)).unwrap();
+        assert!(next.offline);
+        assert_eq!(next.deny, service.policy.deny);
+        // The live policy is untouched until the write is acknowledged
+        assert!(!service.policy.offline);
+
+        assert!(service
+            .apply_update(update(r#"{"allow":["https://Example.com/"]}"#))
+            .is_err());
+    }
+
+    #[test]
+    fn test_requests_deferred_until_loaded() {
+        let mut service = NetworkService::default();
+        let msg = mock_message(net::MSG_NET_REQUEST, 7, Vec::new());
+        service.dispatch_request(msg).unwrap();
+        assert_eq!(service.deferred.len(), 1);
+        assert!(service.pending_ops.is_empty());
+    }
+
+    #[test]
+    fn test_offline_refuses_and_counts() {
+        let mut service = loaded_service();
+        service.policy.offline = true;
+        let request = HttpRequest::get("https://example.com/");
+        assert_eq!(
+            service.check_policy(7, &request),
+            Err(NetworkError::Offline)
+        );
+        assert_eq!(service.traffic[&7].denied, 1);
+        assert!(service.stats_dirty);
+
+        // Malformed URLs aren't a policy refusal
+        service.policy.offline = false;
+        let request = HttpRequest::get("not a url");
+        assert_eq!(
+            service.check_policy(7, &request),
+            Err(NetworkError::InvalidUrl)
+        );
+        assert_eq!(service.traffic[&7].denied, 1);
+    }
+
+    #[test]
+    fn test_going_offline_fails_in_flight() {
+        let mut service = loaded_service();
+        service.pending_ops.insert(
+            42,
+            PendingRequest {
+                client_pid: 7,
+                client_request_id: 1,
+                cache: CachePlan::Bypass,
+                job: None,
+            },
+        );
+        let policy = service.apply_update(update(r#"{"offline":true}"#)).unwrap();
+        service.vfs_ops.insert(
+            1,
+            (
+                VfsOp::CommitPolicy {
+                    client_pid: 3,
+                    cap_slots: Vec::new(),
+                    policy,
+                },
+                OpType::Write,
+            ),
+        );
+
+        let ack = br#"{"result":{"Ok":null}}"#.to_vec();
+        service
+            .handle_vfs_write_response(&mock_message(vfs_msg::MSG_VFS_WRITE_RESPONSE, 4, ack))
+            .unwrap();
+        assert!(service.policy.offline);
+        assert!(service.pending_ops.is_empty());
+        assert!(service.vfs_ops.is_empty());
+    }
+
+    #[test]
+    fn test_traffic_counters_bounded() {
+        let mut service = loaded_service();
+        service.record_sent(7, 100);
+        service.record_received(7, 250);
+        assert_eq!(
+            service.traffic[&7],
+            AppTraffic {
+                requests: 1,
+                denied: 0,
+                bytes_sent: 100,
+                bytes_received: 250,
+            }
+        );
+
+        for pid in 100..100 + MAX_TRACKED_APPS as u32 {
+            service.record_denied(pid);
+        }
+        assert_eq!(service.traffic.len(), MAX_TRACKED_APPS);
+        // The lowest PID made room
+        assert!(!service.traffic.contains_key(&7));
+
+        service.publish_stats();
+        assert!(!service.stats_dirty);
+    }
+
+    // -------------------------------------------------------------------------
+    // Cache tests
+    // -------------------------------------------------------------------------
+
+    fn cached_service(url: &str, cache_control: &str) -> (NetworkService, String) {
+        let mut service = loaded_service();
+        let response = zos_network::HttpSuccess {
+            status: 200,
+            headers: alloc::vec![
+                ("cache-control".into(), cache_control.into()),
+                ("etag".into(), "\"v1\"".into()),
+            ],
+            body: b"cached".to_vec(),
+        };
+        let (id, entry) =
+            CacheEntry::from_response(&HttpRequest::get(url), &response, syscall::get_wallclock())
+                .unwrap();
+        service.cache.insert(id.clone(), entry);
+        (service, id)
+    }
+
+    #[test]
+    fn test_fresh_entry_served_without_fetch() {
+        let (mut service, id) = cached_service("https://example.com/a", "max-age=60");
+        let request = HttpRequest::get("https://example.com/a");
+        let CacheDecision::Serve { id: served, .. } = service.consult_cache(&request) else {
+            panic!("expected a cache hit");
+        };
+        assert_eq!(served, id);
+        assert_eq!(service.cache_stats.misses, 0);
+    }
+
+    #[test]
+    fn test_not_modified_answered_from_cache() {
+        let (mut service, id) = cached_service("https://example.com/a", "no-cache");
+        let request = HttpRequest::get("https://example.com/a");
+        let CacheDecision::Fetch {
+            conditional: Some(conditional),
+            plan,
+        } = service.consult_cache(&request)
+        else {
+            panic!("expected revalidation");
+        };
+        assert!(conditional
+            .headers
+            .iter()
+            .any(|(k, _)| k == "If-None-Match"));
+
+        let mut pending = PendingRequest {
+            client_pid: 7,
+            client_request_id: 1,
+            cache: plan,
+            job: None,
+        };
+        let not_modified = serde_json::to_vec(&HttpResponse::ok(304, Vec::new(), Vec::new()))
+            .unwrap();
+        assert!(service.cache_result(&mut pending, &not_modified).unwrap());
+        assert_eq!(service.cache_stats.revalidated, 1);
+        // The body is read back to answer the client
+        assert!(matches!(
+            service.vfs_ops.values().next(),
+            Some((VfsOp::ServeCached { id: serving, .. }, OpType::Read)) if *serving == id
+        ));
+    }
+
+    #[test]
+    fn test_unsafe_method_invalidates_url() {
+        let (mut service, _) = cached_service("https://example.com/a", "max-age=60");
+        let request = HttpRequest::post("https://example.com/a");
+        assert!(matches!(
+            service.consult_cache(&request),
+            CacheDecision::Fetch {
+                plan: CachePlan::Bypass,
+                ..
+            }
+        ));
+        assert!(service.cache.entries.is_empty());
+        assert!(service.cache_dirty);
+    }
+
+    #[test]
+    fn test_purge_discards_in_flight_store() {
+        let (mut service, _) = cached_service("https://example.com/a", "max-age=60");
+        let response = zos_network::HttpSuccess {
+            status: 200,
+            headers: alloc::vec![("cache-control".into(), "max-age=60".into())],
+            body: b"new".to_vec(),
+        };
+        let (id, entry) =
+            CacheEntry::from_response(&HttpRequest::get("https://example.com/b"), &response, 0)
+                .unwrap();
+        let generation = service.cache_generation;
+
+        let purge = mock_message(net::MSG_NET_CACHE_PURGE, 3, Vec::new());
+        service.handle_cache_purge(&purge).unwrap();
+        assert!(service.cache.entries.is_empty());
+
+        service
+            .finish_store_cached(id, entry, generation, Ok(()))
+            .unwrap();
+        assert!(service.cache.entries.is_empty());
+        assert_eq!(service.cache_stats.stored, 0);
+    }
+
+    #[test]
+    fn test_purge_requires_trusted_pid() {
+        let (mut service, _) = cached_service("https://example.com/a", "max-age=60");
+        let purge = mock_message(net::MSG_NET_CACHE_PURGE, 7, Vec::new());
+        service.handle_cache_purge(&purge).unwrap();
+        assert_eq!(service.cache.entries.len(), 1);
+    }
+
+    // -------------------------------------------------------------------------
+    // Durable job tests
+    // -------------------------------------------------------------------------
+
+    fn journaled_service(url: &str) -> (NetworkService, u32) {
+        let mut service = loaded_service();
+        let request = HttpRequest::get(url).durable("manifest");
+        let id = service.jobs.add("update", request).unwrap();
+        (service, id)
+    }
+
+    fn net_result(request_id: u32, response: &HttpResponse) -> Message {
+        let body = serde_json::to_vec(response).unwrap();
+        let mut data = request_id.to_le_bytes().to_vec();
+        data.push(net_result::NET_OK);
+        data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        data.extend_from_slice(&body);
+        mock_message(net::MSG_NET_RESULT, 0, data)
+    }
+
+    fn job_pending(client_pid: u32, id: u32) -> PendingRequest {
+        PendingRequest {
+            client_pid,
+            client_request_id: 1,
+            cache: CachePlan::Bypass,
+            job: Some(id),
+        }
+    }
+
+    #[test]
+    fn test_live_job_answered_and_dropped() {
+        let (mut service, id) = journaled_service("https://example.com/a");
+        service.live_jobs.insert(id, (7, 1));
+        service.pending_ops.insert(42, job_pending(7, id));
+
+        let response = HttpResponse::ok(200, Vec::new(), b"ok".to_vec());
+        service
+            .handle_net_result(&net_result(42, &response))
+            .unwrap();
+        assert!(service.jobs.jobs.is_empty());
+        assert!(service.live_jobs.is_empty());
+        assert!(matches!(
+            service.vfs_ops.values().next(),
+            Some((VfsOp::CommitJobs { send }, OpType::Write)) if send.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_restored_job_result_kept_for_owner() {
+        let (mut service, id) = journaled_service("https://example.com/a");
+        service.pending_ops.insert(42, job_pending(0, id));
+
+        let response = HttpResponse::ok(200, Vec::new(), b"ok".to_vec());
+        service
+            .handle_net_result(&net_result(42, &response))
+            .unwrap();
+        assert!(matches!(
+            service.take_vfs_op(OpType::Write),
+            Some(VfsOp::StoreJobResult { id: stored }) if stored == id
+        ));
+        // Restored jobs aren't counted against anyone's traffic
+        assert!(service.traffic.is_empty());
+
+        service.finish_store_job_result(id, Ok(())).unwrap();
+        assert_eq!(service.jobs.get(id).unwrap().state, JobState::Done);
+    }
+
+    #[test]
+    fn test_failed_journal_write_fails_job() {
+        let (mut service, id) = journaled_service("https://example.com/a");
+        service.live_jobs.insert(id, (7, 1));
+        service
+            .finish_jobs_commit(alloc::vec![id], Err("disk full".into()))
+            .unwrap();
+        assert!(service.jobs.jobs.is_empty());
+        assert!(service.queued_jobs.is_empty());
+        assert!(service.pending_ops.is_empty());
+    }
+
+    #[test]
+    fn test_restored_jobs_resent_after_journal_write() {
+        let mut service = NetworkService {
+            policy_loaded: true,
+            cache_loaded: true,
+            ..Default::default()
+        };
+        let mut journal = JobJournal::default();
+        let get = journal
+            .add(
+                "update",
+                HttpRequest::get("https://example.com/a").durable("a"),
+            )
+            .unwrap();
+        let post = journal
+            .add(
+                "update",
+                HttpRequest::post("https://example.com/b").durable("b"),
+            )
+            .unwrap();
+        service.finish_jobs_load(Ok(journal.to_json())).unwrap();
+        assert!(service.is_ready());
+        assert!(matches!(
+            service.jobs.get(post).unwrap().state,
+            JobState::Failed(_)
+        ));
+        // Resent only once the journal counting the attempt is written
+        assert!(matches!(
+            service.vfs_ops.values().next(),
+            Some((VfsOp::CommitJobs { send }, OpType::Write)) if *send == alloc::vec![get]
+        ));
+    }
+
+    #[test]
+    fn test_job_ack_requires_owner() {
+        let (mut service, id) = journaled_service("https://example.com/a");
+        service.jobs.get_mut(id).unwrap().state = JobState::Done;
+        let ack = br#"{"job":"manifest"}"#.to_vec();
+        service
+            .handle_job_ack(&mock_message(net::MSG_NET_JOB_ACK, 7, ack))
+            .unwrap();
+        assert!(service.jobs.get(id).is_some());
+    }
 }
@@ -0,0 +1,263 @@
+//! Network policy handlers
+//!
+//! The policy is loaded from [`POLICY_PATH`] at startup and changed only by
+//! MSG_NET_POLICY_SET, which persists the new policy before it takes
+//! effect. Requests that arrive before the load completes are deferred (as
+//! they are until the cache index loads), so nothing is fetched under a
+//! policy the service hasn't read yet.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_network::policy::{NetworkPolicy, POLICY_PATH};
+use zos_network::{HttpResponse, NetworkError};
+use zos_process::net;
+
+use super::{NetworkService, VfsOp, MAX_DEFERRED_MESSAGES, MAX_VFS_OPS};
+
+/// PIDs allowed to change the policy and read traffic counters.
+/// - PID 0: Supervisor
+/// - PID 1: Init
+/// - PID 3: Desktop/Settings UI
+pub(super) const TRUSTED_PIDS_FOR_POLICY: &[u32] = &[0, 1, 3];
+
+/// Payload of MSG_NET_POLICY_SET; absent fields keep their current value.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub(super) struct PolicyUpdate {
+    #[serde(default)]
+    offline: Option<bool>,
+    #[serde(default)]
+    allow: Option<Vec<String>>,
+    #[serde(default)]
+    deny: Option<Vec<String>>,
+}
+
+impl NetworkService {
+    /// Check if caller may change the policy (Rule 4: fail-closed).
+    pub(super) fn check_policy_permission(&self, from_pid: u32) -> bool {
+        let allowed = TRUSTED_PIDS_FOR_POLICY.contains(&from_pid);
+        if !allowed {
+            syscall::debug(&format!(
+                "NetworkService: SECURITY - policy access denied for PID {}",
+                from_pid
+            ));
+        }
+        allowed
+    }
+
+    /// Apply an update to a copy of the policy.
+    pub(super) fn apply_update(&self, update: PolicyUpdate) -> Result<NetworkPolicy, String> {
+        let mut next = self.policy.clone();
+        if let Some(offline) = update.offline {
+            next.offline = offline;
+        }
+        if let Some(allow) = update.allow {
+            next.allow = allow;
+        }
+        if let Some(deny) = update.deny {
+            next.deny = deny;
+        }
+        next.validate()?;
+        Ok(next)
+    }
+
+    pub(super) fn start_policy_load(&mut self) -> Result<(), AppError> {
+        self.start_vfs_read(POLICY_PATH, VfsOp::LoadPolicy)
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_NET_POLICY_GET
+    pub(super) fn handle_policy_get(&mut self, msg: &Message) -> Result<(), AppError> {
+        self.send_service_response(
+            msg.from_pid,
+            &msg.cap_slots,
+            net::MSG_NET_POLICY_GET_RESPONSE,
+            &self.policy.to_json(),
+        )
+    }
+
+    /// Handle MSG_NET_POLICY_SET
+    pub(super) fn handle_policy_set(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = net::MSG_NET_POLICY_SET_RESPONSE;
+
+        if !self.check_policy_permission(msg.from_pid) {
+            return self.send_service_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: NET_POLICY_SET requires system privilege",
+            );
+        }
+        if self.vfs_ops.len() >= MAX_VFS_OPS {
+            return self.send_service_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Service busy: pending policy change limit reached",
+            );
+        }
+
+        let update: PolicyUpdate = match serde_json::from_slice(&msg.data) {
+            Ok(u) => u,
+            Err(_) => {
+                return self.send_service_error(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid policy request: expected {\"offline\": bool?, \"allow\": [string]?, \"deny\": [string]?}",
+                );
+            }
+        };
+        let policy = match self.apply_update(update) {
+            Ok(p) => p,
+            Err(e) => return self.send_service_error(msg.from_pid, &msg.cap_slots, tag, &e),
+        };
+
+        syscall::debug(&format!(
+            "NetworkService: PID {} sets policy offline={} allow={} deny={}",
+            msg.from_pid,
+            policy.offline,
+            policy.allow.len(),
+            policy.deny.len()
+        ));
+
+        let value = policy.to_json();
+        let op = VfsOp::CommitPolicy {
+            client_pid: msg.from_pid,
+            cap_slots: msg.cap_slots.clone(),
+            policy,
+        };
+        if let Err(e) = self.start_vfs_write(POLICY_PATH, &value, op) {
+            return self.send_service_error(msg.from_pid, &msg.cap_slots, tag, &e.to_string());
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Complete the initial policy load.
+    pub(super) fn finish_policy_load(
+        &mut self,
+        result: Result<Vec<u8>, String>,
+    ) -> Result<(), AppError> {
+        self.policy = match result {
+            Ok(data) => NetworkPolicy::from_json(&data)
+                .filter(|policy| policy.validate().is_ok())
+                .unwrap_or_else(fail_closed),
+            // Missing on first boot
+            Err(_) => NetworkPolicy::default(),
+        };
+        self.policy_loaded = true;
+        self.stats_dirty = true;
+        syscall::debug(&format!(
+            "NetworkService: loaded policy offline={} allow={} deny={}",
+            self.policy.offline,
+            self.policy.allow.len(),
+            self.policy.deny.len()
+        ));
+        self.replay_deferred()
+    }
+
+    /// Complete a policy change once its write is acknowledged.
+    pub(super) fn finish_policy_commit(
+        &mut self,
+        client_pid: u32,
+        cap_slots: &[u32],
+        policy: NetworkPolicy,
+        result: Result<(), String>,
+    ) -> Result<(), AppError> {
+        let tag = net::MSG_NET_POLICY_SET_RESPONSE;
+
+        match result {
+            Ok(()) => {
+                let going_offline = policy.offline && !self.policy.offline;
+                self.policy = policy;
+                self.stats_dirty = true;
+                if going_offline {
+                    self.fail_in_flight(NetworkError::Offline)?;
+                }
+                self.send_service_response(client_pid, cap_slots, tag, &self.policy.to_json())
+            }
+            Err(e) => self.send_service_error(
+                client_pid,
+                cap_slots,
+                tag,
+                &format!("VFS write failed for {}: {}", POLICY_PATH, e),
+            ),
+        }
+    }
+
+    /// Fail every in-flight request with `error`, durable jobs included.
+    ///
+    /// Their results are dropped as unknown when they arrive.
+    fn fail_in_flight(&mut self, error: NetworkError) -> Result<(), AppError> {
+        for (_, pending) in core::mem::take(&mut self.pending_ops) {
+            if let Some(id) = pending.job {
+                self.finish_job(
+                    id,
+                    pending.client_pid,
+                    pending.client_request_id,
+                    HttpResponse::err(error.clone()),
+                )?;
+                continue;
+            }
+            self.send_error_response(pending.client_pid, pending.client_request_id, &error)?;
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // Deferral until the policy is loaded
+    // =========================================================================
+
+    /// Buffer a request until the policy and cache index are loaded.
+    pub(super) fn defer(&mut self, msg: Message) -> Result<(), AppError> {
+        if self.deferred.len() < MAX_DEFERRED_MESSAGES {
+            self.deferred.push(msg);
+            return Ok(());
+        }
+        let busy = "Service busy: network policy and cache still loading";
+        if msg.tag == net::MSG_NET_REQUEST {
+            let client_request_id = self.alloc_client_request_id();
+            self.send_error_response(
+                msg.from_pid,
+                client_request_id,
+                &NetworkError::Other(busy.into()),
+            )
+        } else {
+            self.send_service_error(msg.from_pid, &msg.cap_slots, msg.tag + 1, busy)
+        }
+    }
+
+    /// Handle the requests buffered while loading, once everything is.
+    pub(super) fn replay_deferred(&mut self) -> Result<(), AppError> {
+        if !self.is_ready() {
+            return Ok(());
+        }
+        for msg in core::mem::take(&mut self.deferred) {
+            self.dispatch_request(msg)?;
+        }
+        self.send_queued_jobs()
+    }
+}
+
+/// Policy to use when the stored one doesn't parse: offline until Settings
+/// writes a good one, rather than silently dropping its deny list.
+fn fail_closed() -> NetworkPolicy {
+    syscall::debug(&format!(
+        "NetworkService: {} is corrupt, staying offline until the policy is set again",
+        POLICY_PATH
+    ));
+    NetworkPolicy {
+        offline: true,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,82 @@
+//! Per-process traffic and cache counters
+//!
+//! Counted as requests pass through the service and published for the
+//! supervisor's metrics as `NET:STATS:{hex_json}` at most once per update
+//! tick, whenever they changed.
+
+use alloc::format;
+use alloc::string::String;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_network::policy::{AppTraffic, NetworkStats};
+use zos_process::net;
+
+use super::{NetworkService, MAX_TRACKED_APPS};
+
+impl NetworkService {
+    /// Counters for `pid`, making room if needed.
+    ///
+    /// PIDs are not reused, so once the table is full the lowest PID is
+    /// the likeliest to have exited and is the one dropped.
+    fn traffic_mut(&mut self, pid: u32) -> &mut AppTraffic {
+        if !self.traffic.contains_key(&pid) && self.traffic.len() >= MAX_TRACKED_APPS {
+            self.traffic.pop_first();
+        }
+        self.stats_dirty = true;
+        self.traffic.entry(pid).or_default()
+    }
+
+    /// Count a request forwarded to the network.
+    pub(super) fn record_sent(&mut self, pid: u32, bytes: usize) {
+        let traffic = self.traffic_mut(pid);
+        traffic.requests += 1;
+        traffic.bytes_sent += bytes as u64;
+    }
+
+    /// Count a response delivered to a client.
+    pub(super) fn record_received(&mut self, pid: u32, bytes: usize) {
+        self.traffic_mut(pid).bytes_received += bytes as u64;
+    }
+
+    /// Count a request refused by policy.
+    pub(super) fn record_denied(&mut self, pid: u32) {
+        self.traffic_mut(pid).denied += 1;
+    }
+
+    pub(super) fn stats(&self) -> NetworkStats {
+        NetworkStats {
+            offline: self.policy.offline,
+            apps: self.traffic.clone(),
+            cache: self.cache_stats(),
+        }
+    }
+
+    /// Publish the counters for the supervisor if they changed.
+    pub(super) fn publish_stats(&mut self) {
+        if !self.stats_dirty {
+            return;
+        }
+        self.stats_dirty = false;
+        let hex: String = self
+            .stats()
+            .to_json()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        syscall::debug(&format!("{}{}", zos_ipc::debug::NET_STATS, hex));
+    }
+
+    /// Handle MSG_NET_STATS
+    pub(super) fn handle_stats(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = net::MSG_NET_STATS_RESPONSE;
+        if !self.check_policy_permission(msg.from_pid) {
+            return self.send_service_error(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: NET_STATS requires system privilege",
+            );
+        }
+        self.send_service_response(msg.from_pid, &msg.cap_slots, tag, &self.stats().to_json())
+    }
+}
@@ -5,6 +5,7 @@
 //! - Handles capability requests from applications
 //! - Grants/revokes capabilities to/from processes
 //! - Maintains audit trail of all capability operations
+//! - Lets the supervisor audit, revoke and export the grants it recorded
 //!
 //! # Safety Invariants
 //!
@@ -12,6 +13,7 @@
 //! - GRANT: Capability created in target CSpace AND recorded in grants table
 //! - REVOKE: Capability removed from target CSpace AND removed from grants table
 //! - LIST: Accurate list of granted capabilities returned to requester
+//! - REVOKE_GRANT: Capability revoked by the kernel AND grant record removed
 //!
 //! **Acceptable partial failure:**
 //! - Grant syscall fails → error response, no state change
@@ -20,6 +22,8 @@
 //! **Forbidden:**
 //! - Granting capabilities without recording (audit trail gap)
 //! - Processing supervisor commands from non-PID-0 senders (privilege escalation)
+//! - Disclosing or revoking other processes' grants for anyone but the
+//!   supervisor and Init
 //! - Unbounded grants table growth (DoS vector, though less critical than pending ops)
 //!
 //! # Protocol
@@ -30,14 +34,41 @@
 //! - `MSG_REVOKE_CAPABILITY (0x2011)`: Request capability revocation
 //! - `MSG_LIST_MY_CAPS (0x2012)`: Query own capabilities
 //! - `MSG_CAPABILITY_RESPONSE (0x2013)`: Response from PermissionService
+//!
+//! The supervisor (and Init) audit grants for the Security Center with JSON
+//! requests, answered through the reply capability:
+//!
+//! - `MSG_LIST_GRANTS (0x2015)`: Grants per process, with when they were
+//!   granted and the reason the app gave
+//! - `MSG_REVOKE_GRANT (0x2017)`: Revoke one grant through the kernel
+//! - `MSG_EXPORT_GRANTS (0x2019)`: The whole grant table
+//!
+//! The supervisor additionally sends:
+//!
+//! - `MSG_SUPERVISOR_REVOKE_CAP (0x2020)`: Revoke a capability from any process
+//! - `MSG_SUPERVISOR_DELETE_APP_DATA (0x2021)`: Delete an app's data, forwarded
+//!   to VfsService as a signed request (see [`signed_requests`])
+//! - `MSG_SUPERVISOR_PROMPT_RESPONSE (0x2022)`: The user's answer to a prompt
+//!
+//! # Prompts
+//!
+//! Some capabilities are only granted once the user approves: a Debugger
+//! capability lets its holder read any process's memory. PS sends these
+//! requests to the supervisor as `PERMSVC:PROMPT:{hex_json}` on the debug
+//! channel and grants or refuses when the answer comes back. The supervisor
+//! gives PS its Debugger capability after the keystore's.
 
 extern crate alloc;
 
+mod signed_requests;
+
 use alloc::collections::BTreeMap;
 use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
 use crate::manifests::PERMISSION_MANIFEST;
+use serde::Deserialize;
+use serde_json::{json, Value};
 use zos_apps::syscall;
 use zos_apps::{AppContext, AppError, AppManifest, ControlFlow, Message, ZeroApp};
 
@@ -47,11 +78,18 @@ use zos_apps::{AppContext, AppError, AppManifest, ControlFlow, Message, ZeroApp}
 // All IPC message constants are defined in zos-ipc as the single source of truth.
 
 pub use zos_apps::pm::{
-    MSG_CAPABILITY_RESPONSE, MSG_CAPS_LIST_RESPONSE, MSG_LIST_MY_CAPS, MSG_REQUEST_CAPABILITY,
-    MSG_REVOKE_CAPABILITY,
+    MSG_CAPABILITY_RESPONSE, MSG_CAPS_LIST_RESPONSE, MSG_EXPORT_GRANTS, MSG_EXPORT_GRANTS_RESPONSE,
+    MSG_LIST_GRANTS, MSG_LIST_GRANTS_RESPONSE, MSG_LIST_MY_CAPS, MSG_REQUEST_CAPABILITY,
+    MSG_REVOKE_CAPABILITY, MSG_REVOKE_GRANT, MSG_REVOKE_GRANT_RESPONSE,
+};
+
+pub use zos_apps::supervisor::{
+    MSG_SUPERVISOR_DELETE_APP_DATA, MSG_SUPERVISOR_PROMPT_RESPONSE, MSG_SUPERVISOR_REVOKE_CAP,
 };
 
-pub use zos_apps::supervisor::MSG_SUPERVISOR_REVOKE_CAP;
+use signed_requests::SigningState;
+use zos_vfs::client::keystore_async;
+use zos_vfs::ipc::vfs_msg;
 
 // =============================================================================
 // Object Types (re-exported from zos-ipc - single source of truth)
@@ -61,6 +99,24 @@ pub use zos_apps::supervisor::MSG_SUPERVISOR_REVOKE_CAP;
 // This ensures all crates use consistent values when granting/checking capabilities.
 pub use zos_ipc::ObjectType;
 
+// =============================================================================
+// Permission Constants
+// =============================================================================
+
+/// PIDs allowed to audit, revoke and export grants.
+/// - PID 0: Supervisor (on behalf of the Security Center)
+/// - PID 1: Init
+const AUDIT_PIDS: &[u32] = &[0, 1];
+
+/// Version of the `MSG_EXPORT_GRANTS` format
+const EXPORT_VERSION: u32 = 1;
+
+/// Object types granted only once the user approves the request
+const PROMPTED_TYPES: &[ObjectType] = &[ObjectType::Debugger];
+
+/// Most requests waiting for the user at once
+const MAX_PENDING_PROMPTS: usize = 8;
+
 // =============================================================================
 // Permission Tracking
 // =============================================================================
@@ -75,11 +131,38 @@ struct GrantedCap {
     slot: u32,
     /// Permissions granted (read=1, write=2, grant=4)
     permissions: u8,
-    /// Reason for the grant
-    #[allow(dead_code)]
+    /// Wall-clock time of the grant (ms since epoch)
+    granted_at: u64,
+    /// Reason the app gave when it asked for the capability
+    reason: String,
+}
+
+/// A capability request waiting for the user's answer
+#[derive(Clone, Debug)]
+struct PendingPrompt {
+    pid: u32,
+    object_type: ObjectType,
+    /// Root capability to grant from
+    from_slot: u32,
+    permissions: u8,
     reason: String,
 }
 
+/// Payload of MSG_LIST_GRANTS.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct ListGrantsRequest {
+    /// Only this process's grants
+    #[serde(default)]
+    pid: Option<u32>,
+}
+
+/// Payload of MSG_REVOKE_GRANT.
+#[derive(Clone, Debug, Deserialize)]
+struct RevokeGrantRequest {
+    pid: u32,
+    object_type: u8,
+}
+
 // =============================================================================
 // PermissionService Application
 // =============================================================================
@@ -95,6 +178,13 @@ pub struct PermissionService {
     console_cap_slot: Option<u32>,
     spawn_cap_slot: Option<u32>,
     endpoint_cap_slot: Option<u32>,
+
+    /// Requests waiting for the user, by prompt ID
+    pending_prompts: BTreeMap<u32, PendingPrompt>,
+    next_prompt_id: u32,
+
+    /// Service key and signed requests sent to VfsService
+    signing: SigningState,
 }
 
 impl PermissionService {
@@ -113,6 +203,7 @@ impl PermissionService {
             GrantedCap {
                 slot,
                 permissions,
+                granted_at: syscall::get_wallclock(),
                 reason,
             },
         );
@@ -139,6 +230,81 @@ impl PermissionService {
             .collect()
     }
 
+    /// Type of the grant recorded for a process at a slot
+    fn grant_type_at(&self, pid: u32, slot: u32) -> Option<ObjectType> {
+        self.granted_caps
+            .iter()
+            .find(|((p, _), grant)| *p == pid && grant.slot == slot)
+            .and_then(|((_, obj_type), _)| ObjectType::from_u8(*obj_type))
+    }
+
+    /// Revoke a capability from a process through the kernel, drop its
+    /// grant record and have the process told why.
+    fn revoke_from(&mut self, target_pid: u32, slot: u32, reason: u8) -> Result<(), u32> {
+        syscall::cap_revoke_from(target_pid, slot)?;
+
+        if let Some(obj_type) = self.grant_type_at(target_pid, slot) {
+            self.remove_grant(target_pid, obj_type);
+        }
+
+        // Notify the affected process via debug channel
+        // The supervisor or Init can route this notification
+        syscall::debug(&format!(
+            "PERMSVC:REVOKED:{}:{}:{}",
+            target_pid, slot, reason
+        ));
+        Ok(())
+    }
+
+    /// JSON view of one grant
+    fn grant_json(pid: u32, object_type: u8, grant: &GrantedCap) -> Value {
+        json!({
+            "pid": pid,
+            "object_type": object_type,
+            "object_name": ObjectType::from_u8(object_type).map_or("Unknown", |t| t.name()),
+            "slot": grant.slot,
+            "permissions": grant.permissions,
+            "granted_at": grant.granted_at,
+            "reason": grant.reason,
+        })
+    }
+
+    /// Grants grouped by process, of one process only if `pid` is given
+    fn grants_by_app(&self, pid: Option<u32>) -> Value {
+        let mut apps: BTreeMap<u32, Vec<Value>> = BTreeMap::new();
+        for (&(p, obj_type), grant) in &self.granted_caps {
+            if pid.is_none_or(|pid| pid == p) {
+                apps.entry(p)
+                    .or_default()
+                    .push(Self::grant_json(p, obj_type, grant));
+            }
+        }
+        let apps: Vec<Value> = apps
+            .into_iter()
+            .map(|(pid, grants)| json!({ "pid": pid, "grants": grants }))
+            .collect();
+        json!({ "apps": apps })
+    }
+
+    /// The whole grant table, for export
+    fn export_grants(&self, exported_at: u64) -> Value {
+        let grants: Vec<Value> = self
+            .granted_caps
+            .iter()
+            .map(|(&(pid, obj_type), grant)| Self::grant_json(pid, obj_type, grant))
+            .collect();
+        json!({
+            "version": EXPORT_VERSION,
+            "exported_at": exported_at,
+            "grants": grants,
+        })
+    }
+
+    /// Check if caller may audit grants (Rule 4: fail-closed).
+    fn is_auditor(from_pid: u32) -> bool {
+        AUDIT_PIDS.contains(&from_pid)
+    }
+
     /// Handle capability request
     fn handle_cap_request(&mut self, ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
         // Parse request: [object_type: u8, permissions: u8, reason_len: u16, reason: [u8]]
@@ -188,6 +354,7 @@ impl PermissionService {
             ObjectType::Console => self.console_cap_slot,
             ObjectType::Process => self.spawn_cap_slot,
             ObjectType::Endpoint => self.endpoint_cap_slot,
+            ObjectType::Debugger => Self::debugger_cap_slot(),
             _ => {
                 syscall::debug(&format!(
                     "PermSvc: {} not yet supported",
@@ -213,24 +380,60 @@ impl PermissionService {
             }
         };
 
-        // Grant via syscall
+        if PROMPTED_TYPES.contains(&object_type) {
+            let prompt = PendingPrompt {
+                pid: msg.from_pid,
+                object_type,
+                from_slot,
+                permissions,
+                reason,
+            };
+            return match self.queue_prompt(prompt) {
+                Ok(id) => {
+                    self.send_prompt(id);
+                    Ok(())
+                }
+                Err(error) => self.send_error_response(ctx, msg.from_pid, error),
+            };
+        }
+
+        self.grant(
+            ctx,
+            msg.from_pid,
+            object_type,
+            from_slot,
+            permissions,
+            reason,
+        )
+    }
+
+    /// Grant a capability from one of PS's root capabilities and record it
+    fn grant(
+        &mut self,
+        ctx: &AppContext,
+        target_pid: u32,
+        object_type: ObjectType,
+        from_slot: u32,
+        permissions: u8,
+        reason: String,
+    ) -> Result<(), AppError> {
         let perms = syscall::Permissions {
             read: (permissions & 0x01) != 0,
             write: (permissions & 0x02) != 0,
             grant: (permissions & 0x04) != 0,
         };
 
-        match syscall::cap_grant(from_slot, msg.from_pid, perms) {
+        match syscall::cap_grant(from_slot, target_pid, perms) {
             Ok(new_slot) => {
                 syscall::debug(&format!(
                     "PermSvc: Granted {} to PID {} at slot {}",
                     object_type.name(),
-                    msg.from_pid,
+                    target_pid,
                     new_slot
                 ));
 
-                self.record_grant(msg.from_pid, object_type, new_slot, permissions, reason);
-                self.send_success_response(ctx, msg.from_pid, new_slot)
+                self.record_grant(target_pid, object_type, new_slot, permissions, reason);
+                self.send_success_response(ctx, target_pid, new_slot)
             }
             Err(e) => {
                 syscall::debug(&format!(
@@ -241,16 +444,115 @@ impl PermissionService {
                 // Fall back to debug message for supervisor
                 syscall::debug(&format!(
                     "PERMSVC:GRANT:{}:{}:{}",
-                    msg.from_pid, from_slot, permissions
+                    target_pid, from_slot, permissions
                 ));
 
                 // Optimistically record and respond
-                self.record_grant(msg.from_pid, object_type, from_slot, permissions, reason);
-                self.send_success_response(ctx, msg.from_pid, from_slot)
+                self.record_grant(target_pid, object_type, from_slot, permissions, reason);
+                self.send_success_response(ctx, target_pid, from_slot)
             }
         }
     }
 
+    /// Slot of the Debugger capability the supervisor gave PS, if it has
+    fn debugger_cap_slot() -> Option<u32> {
+        syscall::list_caps()
+            .into_iter()
+            .find(|cap| cap.object_type == ObjectType::Debugger as u8)
+            .map(|cap| cap.slot)
+    }
+
+    /// Queue a request for the user's answer.
+    ///
+    /// Returns the prompt ID, or why the request is refused outright.
+    fn queue_prompt(&mut self, prompt: PendingPrompt) -> Result<u32, &'static str> {
+        let asked = self
+            .pending_prompts
+            .values()
+            .any(|p| p.pid == prompt.pid && p.object_type == prompt.object_type);
+        if asked {
+            return Err("Request already waiting for the user");
+        }
+        if self.pending_prompts.len() >= MAX_PENDING_PROMPTS {
+            return Err("Too many requests waiting for the user");
+        }
+        let id = self.next_prompt_id;
+        self.next_prompt_id = id.wrapping_add(1);
+        self.pending_prompts.insert(id, prompt);
+        Ok(id)
+    }
+
+    /// JSON the supervisor shows the user for a prompt
+    fn prompt_json(id: u32, prompt: &PendingPrompt) -> Value {
+        json!({
+            "id": id,
+            "pid": prompt.pid,
+            "object_type": prompt.object_type as u8,
+            "object_name": prompt.object_type.name(),
+            "permissions": prompt.permissions,
+            "reason": prompt.reason,
+        })
+    }
+
+    /// Ask the supervisor to put a queued prompt to the user
+    fn send_prompt(&self, id: u32) {
+        let Some(prompt) = self.pending_prompts.get(&id) else {
+            return;
+        };
+        syscall::debug(&format!(
+            "PermSvc: Asking the user to grant {} to PID {}",
+            prompt.object_type.name(),
+            prompt.pid
+        ));
+        let json = serde_json::to_vec(&Self::prompt_json(id, prompt)).unwrap_or_default();
+        let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+        syscall::debug(&format!("{}{}", zos_ipc::debug::PERMSVC_PROMPT, hex));
+    }
+
+    /// Handle the user's answer to a prompt, relayed by the supervisor.
+    ///
+    /// Payload: [prompt_id: u32, granted: u8]
+    fn handle_prompt_response(&mut self, ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
+        if msg.from_pid != 0 {
+            syscall::debug(&format!(
+                "PermSvc: SECURITY - Prompt response from non-supervisor PID {}",
+                msg.from_pid
+            ));
+            return Ok(());
+        }
+        if msg.data.len() < 5 {
+            syscall::debug("PermSvc: Invalid prompt response payload (too short)");
+            return Ok(());
+        }
+
+        let id = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
+        let Some(prompt) = self.pending_prompts.remove(&id) else {
+            syscall::debug(&format!("PermSvc: Response to unknown prompt {}", id));
+            return Ok(());
+        };
+        if msg.data[4] == 0 {
+            syscall::debug(&format!(
+                "PermSvc: User denied {} to PID {}",
+                prompt.object_type.name(),
+                prompt.pid
+            ));
+            return self.send_error_response(
+                ctx,
+                prompt.pid,
+                &format!("{} denied by the user", prompt.object_type.name()),
+            );
+        }
+
+        self.grant(
+            ctx,
+            prompt.pid,
+            prompt.object_type,
+            prompt.from_slot,
+            prompt.permissions,
+            prompt.reason,
+        )
+    }
+
     /// Handle capability revocation request
     fn handle_cap_revoke(&mut self, ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
         // Parse request: [slot: u32]
@@ -347,30 +649,12 @@ impl PermissionService {
 
         // Perform the revocation via syscall
         // Uses privileged cap_revoke_from to revoke from another process
-        match syscall::cap_revoke_from(target_pid, slot) {
+        match self.revoke_from(target_pid, slot, reason) {
             Ok(()) => {
                 syscall::debug(&format!(
                     "PermSvc: Successfully revoked cap from PID {} slot {}",
                     target_pid, slot
                 ));
-
-                // Remove from our tracking if we have it
-                for obj_type in 1..=8u8 {
-                    let key = (target_pid, obj_type);
-                    if let Some(grant) = self.granted_caps.get(&key) {
-                        if grant.slot == slot {
-                            self.granted_caps.remove(&key);
-                            break;
-                        }
-                    }
-                }
-
-                // Notify the affected process via debug channel
-                // The supervisor or Init can route this notification
-                syscall::debug(&format!(
-                    "PERMSVC:REVOKED:{}:{}:{}",
-                    target_pid, slot, reason
-                ));
             }
             Err(e) => {
                 syscall::debug(&format!(
@@ -383,6 +667,121 @@ impl PermissionService {
         Ok(())
     }
 
+    /// Handle MSG_LIST_GRANTS
+    fn handle_list_grants(&self, msg: &Message) -> Result<(), AppError> {
+        let tag = MSG_LIST_GRANTS_RESPONSE;
+        if !Self::is_auditor(msg.from_pid) {
+            return self.send_audit_denied(msg, tag);
+        }
+        // An empty payload lists every process
+        let request: ListGrantsRequest = if msg.data.is_empty() {
+            ListGrantsRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_json_error(
+                        msg,
+                        tag,
+                        "Invalid list request: expected {\"pid\": u32?}",
+                    );
+                }
+            }
+        };
+        self.send_json_response(msg, tag, &self.grants_by_app(request.pid))
+    }
+
+    /// Handle MSG_REVOKE_GRANT
+    fn handle_revoke_grant(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = MSG_REVOKE_GRANT_RESPONSE;
+        if !Self::is_auditor(msg.from_pid) {
+            return self.send_audit_denied(msg, tag);
+        }
+        let request: RevokeGrantRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_json_error(
+                    msg,
+                    tag,
+                    "Invalid revoke request: expected {\"pid\": u32, \"object_type\": u8}",
+                );
+            }
+        };
+        let Some(object_type) = ObjectType::from_u8(request.object_type) else {
+            return self.send_json_error(msg, tag, "Unknown object type");
+        };
+        let Some(grant) = self.get_grant(request.pid, object_type).cloned() else {
+            return self.send_json_error(msg, tag, "Grant not found");
+        };
+
+        if let Err(e) = self.revoke_from(request.pid, grant.slot, zos_ipc::revoke_reason::EXPLICIT)
+        {
+            syscall::debug(&format!(
+                "PermSvc: Revoke syscall failed for PID {} slot {}: {}",
+                request.pid, grant.slot, e
+            ));
+            return self.send_json_error(msg, tag, &format!("Revoke failed: {}", e));
+        }
+        syscall::debug(&format!(
+            "PermSvc: PID {} revoked {} from PID {} (slot {})",
+            msg.from_pid,
+            object_type.name(),
+            request.pid,
+            grant.slot
+        ));
+        let revoked = Self::grant_json(request.pid, request.object_type, &grant);
+        self.send_json_response(msg, tag, &json!({ "revoked": revoked }))
+    }
+
+    /// Handle MSG_EXPORT_GRANTS
+    fn handle_export_grants(&self, msg: &Message) -> Result<(), AppError> {
+        let tag = MSG_EXPORT_GRANTS_RESPONSE;
+        if !Self::is_auditor(msg.from_pid) {
+            return self.send_audit_denied(msg, tag);
+        }
+        let export = self.export_grants(syscall::get_wallclock());
+        self.send_json_response(msg, tag, &export)
+    }
+
+    /// Refuse an audit request from an unprivileged process
+    fn send_audit_denied(&self, msg: &Message, tag: u32) -> Result<(), AppError> {
+        syscall::debug(&format!(
+            "PermSvc: SECURITY - grant audit request 0x{:x} denied for PID {}",
+            msg.tag, msg.from_pid
+        ));
+        self.send_json_error(
+            msg,
+            tag,
+            "Permission denied: grant audit requires system privilege",
+        )
+    }
+
+    /// Send a JSON error response
+    fn send_json_error(&self, msg: &Message, tag: u32, error: &str) -> Result<(), AppError> {
+        self.send_json_response(msg, tag, &json!({ "error": error }))
+    }
+
+    /// Send a JSON response via reply cap, falling back to the debug channel.
+    fn send_json_response(&self, msg: &Message, tag: u32, json: &Value) -> Result<(), AppError> {
+        let json = serde_json::to_vec(json).unwrap_or_default();
+        if let Some(&reply_slot) = msg.cap_slots.first() {
+            match syscall::send(reply_slot, tag, &json) {
+                Ok(()) => return Ok(()),
+                Err(e) => syscall::debug(&format!(
+                    "PermSvc: Reply cap send failed ({}), falling back to debug channel",
+                    e
+                )),
+            }
+        }
+
+        let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+        syscall::debug(&format!(
+            "SERVICE:RESPONSE:{}:{:08x}:{}",
+            msg.from_pid, tag, hex
+        ));
+        Ok(())
+    }
+
     /// Send success response
     fn send_success_response(
         &self,
@@ -445,6 +844,9 @@ impl ZeroApp for PermissionService {
             self.console_cap_slot, self.spawn_cap_slot, self.endpoint_cap_slot
         ));
 
+        // Load (or create) the key used to sign privileged VFS requests
+        self.start_key_load();
+
         Ok(())
     }
 
@@ -457,7 +859,18 @@ impl ZeroApp for PermissionService {
             MSG_REQUEST_CAPABILITY => self.handle_cap_request(ctx, &msg),
             MSG_REVOKE_CAPABILITY => self.handle_cap_revoke(ctx, &msg),
             MSG_LIST_MY_CAPS => self.handle_list_caps(ctx, &msg),
+            MSG_LIST_GRANTS => self.handle_list_grants(&msg),
+            MSG_REVOKE_GRANT => self.handle_revoke_grant(&msg),
+            MSG_EXPORT_GRANTS => self.handle_export_grants(&msg),
             MSG_SUPERVISOR_REVOKE_CAP => self.handle_supervisor_revoke(&msg),
+            MSG_SUPERVISOR_DELETE_APP_DATA => self.handle_supervisor_delete_app_data(&msg),
+            MSG_SUPERVISOR_PROMPT_RESPONSE => self.handle_prompt_response(ctx, &msg),
+            tag if keystore_async::is_keystore_response(tag) => {
+                self.handle_keystore_response(&msg)
+            }
+            vfs_msg::MSG_VFS_REGISTER_SERVICE_KEY_RESPONSE
+            | vfs_msg::MSG_VFS_SIGNED_REQUEST_RESPONSE
+            | vfs_msg::MSG_VFS_RMDIR_RESPONSE => self.handle_vfs_response(&msg),
             _ => {
                 syscall::debug(&format!(
                     "PermSvc: Unknown message tag 0x{:x} from PID {}",
@@ -569,6 +982,116 @@ mod tests {
         assert_eq!(grants.len(), 2);
     }
 
+    // -------------------------------------------------------------------------
+    // Grant audit tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_grants_by_app_groups_and_filters() {
+        let mut service = PermissionService::default();
+        service.record_grant(20, ObjectType::Console, 3, 0x01, String::from("c"));
+        service.record_grant(10, ObjectType::Console, 1, 0x01, String::from("print logs"));
+        service.record_grant(10, ObjectType::Endpoint, 2, 0x03, String::from("b"));
+
+        let all = service.grants_by_app(None);
+        let apps = all["apps"].as_array().unwrap();
+        assert_eq!(apps.len(), 2);
+        assert_eq!(apps[0]["pid"], 10);
+        assert_eq!(apps[0]["grants"].as_array().unwrap().len(), 2);
+
+        let grant = apps[0]["grants"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|g| g["object_name"] == "Console")
+            .unwrap();
+        assert_eq!(grant["slot"], 1);
+        assert_eq!(grant["reason"], "print logs");
+        assert!(grant["granted_at"].as_u64().unwrap() > 0);
+
+        let one = service.grants_by_app(Some(20));
+        assert_eq!(one["apps"].as_array().unwrap().len(), 1);
+        assert_eq!(one["apps"][0]["pid"], 20);
+        assert!(service.grants_by_app(Some(99))["apps"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_export_grants() {
+        let mut service = PermissionService::default();
+        service.record_grant(10, ObjectType::Console, 1, 0x01, String::from("a"));
+        service.record_grant(20, ObjectType::Process, 4, 0x02, String::from("b"));
+
+        let export = service.export_grants(1234);
+        assert_eq!(export["version"], EXPORT_VERSION);
+        assert_eq!(export["exported_at"], 1234);
+        let grants = export["grants"].as_array().unwrap();
+        assert_eq!(grants.len(), 2);
+        assert_eq!(grants[1]["pid"], 20);
+        assert_eq!(grants[1]["object_type"], ObjectType::Process as u8);
+    }
+
+    #[test]
+    fn test_grant_type_at() {
+        let mut service = PermissionService::default();
+        service.record_grant(10, ObjectType::Endpoint, 7, 0x01, String::from("a"));
+
+        assert_eq!(service.grant_type_at(10, 7), Some(ObjectType::Endpoint));
+        assert_eq!(service.grant_type_at(10, 8), None);
+        assert_eq!(service.grant_type_at(11, 7), None);
+    }
+
+    // -------------------------------------------------------------------------
+    // Prompt tests
+    // -------------------------------------------------------------------------
+
+    fn debugger_prompt(pid: u32) -> PendingPrompt {
+        PendingPrompt {
+            pid,
+            object_type: ObjectType::Debugger,
+            from_slot: 6,
+            permissions: 0x01,
+            reason: String::from("inspect a stuck service"),
+        }
+    }
+
+    #[test]
+    fn test_debugger_requests_are_prompted() {
+        assert!(PROMPTED_TYPES.contains(&ObjectType::Debugger));
+        assert!(!PROMPTED_TYPES.contains(&ObjectType::Console));
+
+        let mut service = PermissionService::default();
+        let id = service.queue_prompt(debugger_prompt(10)).unwrap();
+        let json = PermissionService::prompt_json(id, &service.pending_prompts[&id]);
+        assert_eq!(json["pid"], 10);
+        assert_eq!(json["object_name"], "Debugger");
+        assert_eq!(json["reason"], "inspect a stuck service");
+    }
+
+    #[test]
+    fn test_pending_prompts_are_bounded() {
+        let mut service = PermissionService::default();
+        service.queue_prompt(debugger_prompt(10)).unwrap();
+        // Asking again while the user decides is refused
+        assert!(service.queue_prompt(debugger_prompt(10)).is_err());
+
+        for pid in 11..10 + MAX_PENDING_PROMPTS as u32 {
+            service.queue_prompt(debugger_prompt(pid)).unwrap();
+        }
+        assert!(service.queue_prompt(debugger_prompt(99)).is_err());
+        assert_eq!(service.pending_prompts.len(), MAX_PENDING_PROMPTS);
+    }
+
+    #[test]
+    fn test_only_system_processes_audit_grants() {
+        assert!(PermissionService::is_auditor(0));
+        assert!(PermissionService::is_auditor(1));
+        assert!(!PermissionService::is_auditor(2));
+        assert!(!PermissionService::is_auditor(42));
+    }
+
     // -------------------------------------------------------------------------
     // Authorization check tests (Rule 4: fail-closed)
     // -------------------------------------------------------------------------
@@ -0,0 +1,284 @@
+//! Signed requests to VfsService
+//!
+//! PermissionService orders privileged filesystem operations, such as
+//! deleting an app's data, as signed requests so VfsService can check who
+//! ordered them and keep an audit trail.
+//!
+//! At start the service key is read from KeystoreService, or created and
+//! stored there on first boot. Its public half is then registered with
+//! VfsService. Requests arriving before the key is ready are refused.
+
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_identity::crypto::NeuralKey;
+use zos_ipc::keystore_svc;
+use zos_vfs::client::async_ops::VFS_ENDPOINT_SLOT;
+use zos_vfs::client::keystore_async::{self, KeystoreError, KeystoreReadResponse};
+use zos_vfs::ipc::{
+    vfs_msg, RegisterServiceKeyRequest, RegisterServiceKeyResponse, RmdirRequest, RmdirResponse,
+};
+
+use super::PermissionService;
+use crate::services::identity::utils::bytes_to_hex;
+use crate::signing::{RequestSigner, ServiceKey};
+
+/// Name this service signs as.
+pub const SERVICE_NAME: &str = "permission";
+
+/// Maximum app data deletions awaiting a VFS response (Rule 11).
+const MAX_PENDING_DELETES: usize = 16;
+
+/// Progress of loading the service key.
+#[derive(Default)]
+pub(super) enum KeyLoad {
+    /// Not started
+    #[default]
+    Idle,
+    /// Waiting for the keystore read
+    Reading,
+    /// First boot: waiting for the new key to be stored
+    Writing(ServiceKey),
+    /// Key loaded (or loading failed; see `signer`)
+    Done,
+}
+
+/// State for signed requests.
+#[derive(Default)]
+pub(super) struct SigningState {
+    pub(super) key_load: KeyLoad,
+    pub(super) signer: Option<RequestSigner>,
+    /// Directories whose deletion was sent, oldest first
+    pub(super) pending_deletes: VecDeque<String>,
+}
+
+impl PermissionService {
+    /// Start loading the service key from the keystore.
+    pub(super) fn start_key_load(&mut self) {
+        let path = ServiceKey::storage_path(SERVICE_NAME);
+        match keystore_async::send_read_request(&path) {
+            Ok(()) => self.signing.key_load = KeyLoad::Reading,
+            Err(e) => {
+                syscall::debug(&format!(
+                    "PermSvc: Cannot read service key, signed requests disabled: {:?}",
+                    e
+                ));
+                self.signing.key_load = KeyLoad::Done;
+            }
+        }
+    }
+
+    /// Handle a keystore response for the service key.
+    pub(super) fn handle_keystore_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        match (core::mem::take(&mut self.signing.key_load), msg.tag) {
+            (KeyLoad::Reading, keystore_svc::MSG_KEYSTORE_READ_RESPONSE) => {
+                self.signing.key_load = KeyLoad::Done;
+                let response = serde_json::from_slice::<KeystoreReadResponse>(&msg.data);
+                // Only a missing key is replaced; anything else would orphan
+                // the key VfsService has already pinned
+                match response.map(|r| r.result) {
+                    Ok(Ok(data)) => match serde_json::from_slice::<ServiceKey>(&data) {
+                        Ok(key) => self.install_key(key),
+                        Err(e) => syscall::debug(&format!(
+                            "PermSvc: Stored service key unreadable, signed requests disabled: {}",
+                            e
+                        )),
+                    },
+                    Ok(Err(KeystoreError::NotFound)) => self.create_key(),
+                    Ok(Err(e)) => syscall::debug(&format!(
+                        "PermSvc: Service key read failed, signed requests disabled: {:?}",
+                        e
+                    )),
+                    Err(e) => syscall::debug(&format!("PermSvc: Bad keystore response: {}", e)),
+                }
+            }
+            (KeyLoad::Writing(key), keystore_svc::MSG_KEYSTORE_WRITE_RESPONSE) => {
+                match keystore_async::parse_write_response(&msg.data) {
+                    Ok(()) => self.install_key(key),
+                    Err(e) => {
+                        syscall::debug(&format!(
+                            "PermSvc: Storing service key failed, signed requests disabled: {}",
+                            e
+                        ));
+                        self.signing.key_load = KeyLoad::Done;
+                    }
+                }
+            }
+            (state, tag) => {
+                syscall::debug(&format!(
+                    "PermSvc: Unexpected keystore response 0x{:x}",
+                    tag
+                ));
+                self.signing.key_load = state;
+            }
+        }
+        Ok(())
+    }
+
+    /// First boot: generate a key and store it before use.
+    fn create_key(&mut self) {
+        let seed = match NeuralKey::generate() {
+            Ok(seed) => *seed.as_bytes(),
+            Err(e) => {
+                syscall::debug(&format!("PermSvc: Service key generation failed: {:?}", e));
+                return;
+            }
+        };
+        let key = ServiceKey::from_seed(SERVICE_NAME, seed, syscall::get_wallclock());
+        let json = match serde_json::to_vec(&key) {
+            Ok(json) => json,
+            Err(e) => {
+                syscall::debug(&format!("PermSvc: Service key serialization failed: {}", e));
+                return;
+            }
+        };
+        let path = ServiceKey::storage_path(SERVICE_NAME);
+        match keystore_async::send_write_request(&path, &json) {
+            Ok(()) => {
+                syscall::debug("PermSvc: Created service key");
+                self.signing.key_load = KeyLoad::Writing(key);
+            }
+            Err(e) => syscall::debug(&format!("PermSvc: Cannot store service key: {:?}", e)),
+        }
+    }
+
+    /// Start signing with `key` and register its public half with VFS.
+    fn install_key(&mut self, key: ServiceKey) {
+        let request = RegisterServiceKeyRequest {
+            service: String::from(SERVICE_NAME),
+            public_key: bytes_to_hex(&key.public_key()),
+        };
+        self.signing.signer = Some(RequestSigner::new(key));
+        self.signing.key_load = KeyLoad::Done;
+
+        if let Ok(data) = serde_json::to_vec(&request) {
+            if let Err(e) = syscall::send(
+                VFS_ENDPOINT_SLOT,
+                vfs_msg::MSG_VFS_REGISTER_SERVICE_KEY,
+                &data,
+            ) {
+                syscall::debug(&format!(
+                    "PermSvc: Service key registration send failed: {}",
+                    e
+                ));
+            }
+        }
+    }
+
+    /// Handle supervisor request to delete an app's data.
+    ///
+    /// Payload: [user_id: u128, app_id: [u8]]
+    pub(super) fn handle_supervisor_delete_app_data(
+        &mut self,
+        msg: &Message,
+    ) -> Result<(), AppError> {
+        if msg.from_pid != zos_process::pid::SUPERVISOR {
+            syscall::debug(&format!(
+                "PermSvc: SECURITY - Delete app data request from non-supervisor PID {}",
+                msg.from_pid
+            ));
+            return Ok(());
+        }
+        let Some(path) = parse_app_data_request(&msg.data) else {
+            syscall::debug("PermSvc: Invalid delete app data payload");
+            return Ok(());
+        };
+        if self.signing.pending_deletes.len() >= MAX_PENDING_DELETES {
+            syscall::debug(&format!(
+                "PermSvc: Too many pending deletes, dropping {}",
+                path
+            ));
+            return Ok(());
+        }
+        let Some(signer) = self.signing.signer.as_mut() else {
+            syscall::debug(&format!(
+                "PermSvc: Service key not loaded, cannot delete {}",
+                path
+            ));
+            return Ok(());
+        };
+
+        let inner = RmdirRequest {
+            path: path.clone(),
+            recursive: true,
+        };
+        let payload = serde_json::to_string(&inner)
+            .map_err(|e| AppError::IpcError(format!("Serialize failed: {}", e)))?;
+        let request = signer.sign("rmdir", payload, syscall::get_wallclock());
+        let data = serde_json::to_vec(&request)
+            .map_err(|e| AppError::IpcError(format!("Serialize failed: {}", e)))?;
+        syscall::send(VFS_ENDPOINT_SLOT, vfs_msg::MSG_VFS_SIGNED_REQUEST, &data)
+            .map_err(|e| AppError::IpcError(format!("Send failed: {}", e)))?;
+
+        syscall::debug(&format!(
+            "PermSvc: Ordered deletion of {} (nonce {})",
+            path, request.nonce
+        ));
+        self.signing.pending_deletes.push_back(path);
+        Ok(())
+    }
+
+    /// Handle a VFS response to a registration or signed request.
+    pub(super) fn handle_vfs_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        if msg.tag == vfs_msg::MSG_VFS_REGISTER_SERVICE_KEY_RESPONSE {
+            match serde_json::from_slice::<RegisterServiceKeyResponse>(&msg.data) {
+                Ok(RegisterServiceKeyResponse { result: Ok(()) }) => {
+                    syscall::debug("PermSvc: Service key registered with VFS");
+                }
+                Ok(RegisterServiceKeyResponse { result: Err(e) }) => {
+                    syscall::debug(&format!("PermSvc: VFS refused service key: {:?}", e));
+                }
+                Err(e) => syscall::debug(&format!("PermSvc: Bad VFS response: {}", e)),
+            }
+            return Ok(());
+        }
+
+        let path = self.signing.pending_deletes.pop_front().unwrap_or_default();
+        let outcome = match serde_json::from_slice::<RmdirResponse>(&msg.data) {
+            Ok(RmdirResponse { result: Ok(()), .. }) => String::from("ok"),
+            Ok(RmdirResponse { result: Err(e), .. }) => format!("{:?}", e),
+            Err(e) => format!("bad response: {}", e),
+        };
+        syscall::debug(&format!("PERMSVC:APP_DATA_DELETED:{}:{}", path, outcome));
+        Ok(())
+    }
+}
+
+/// Parse `[user_id: u128, app_id: [u8]]` into the app's home directory.
+///
+/// The app ID must be a single path component.
+fn parse_app_data_request(data: &[u8]) -> Option<String> {
+    let user_id = u128::from_le_bytes(data.get(..16)?.try_into().ok()?);
+    let app_id = core::str::from_utf8(data.get(16..)?).ok()?;
+    let valid = !app_id.is_empty()
+        && app_id.len() <= 128
+        && !app_id.starts_with('.')
+        && app_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-');
+    valid.then(|| format!("/home/{}/Apps/{}", user_id, app_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_app_data_request() {
+        let mut data = 42u128.to_le_bytes().to_vec();
+        data.extend_from_slice(b"com.example.notes");
+        assert_eq!(
+            parse_app_data_request(&data).as_deref(),
+            Some("/home/42/Apps/com.example.notes")
+        );
+
+        for bad in [&b""[..], b"..", b"../etc", b"a/b"] {
+            let mut data = 42u128.to_le_bytes().to_vec();
+            data.extend_from_slice(bad);
+            assert_eq!(parse_app_data_request(&data), None);
+        }
+        assert_eq!(parse_app_data_request(&[0; 8]), None);
+    }
+}
@@ -0,0 +1,380 @@
+//! Print documents
+//!
+//! Apps describe what to print in one of two forms:
+//!
+//! - **Pages**: a list of pages, each a list of text runs, lines and
+//!   rectangles positioned in points (1/72 inch) from the page's top-left
+//!   corner. Pages can go to the print dialog or be rendered to PDF (see
+//!   [`super::pdf`]).
+//! - **HTML**: a self-contained HTML document, laid out by the browser.
+//!   HTML can only go to the print dialog.
+//!
+//! Documents are checked against the limits below before a job is created
+//! (Rule 11: resource limits).
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Maximum pages in one document
+pub const MAX_PAGES: usize = 500;
+
+/// Maximum items on one page
+pub const MAX_ITEMS_PER_PAGE: usize = 2_000;
+
+/// Maximum items in one document
+pub const MAX_ITEMS: usize = 50_000;
+
+/// Maximum length of one text run, in bytes
+pub const MAX_TEXT_LEN: usize = 4_096;
+
+/// Maximum length of an HTML document, in bytes
+pub const MAX_HTML_LEN: usize = 1024 * 1024;
+
+/// Maximum length of a job title, in bytes
+pub const MAX_TITLE_LEN: usize = 200;
+
+/// Smallest and largest page edge, in points (1 inch to 200 inches)
+pub const PAGE_EDGE_RANGE: (f32, f32) = (72.0, 14_400.0);
+
+/// Largest font size, in points
+pub const MAX_FONT_SIZE: f32 = 1_000.0;
+
+/// An sRGB color as `[r, g, b]`.
+pub type Color = [u8; 3];
+
+/// Black, the default for text and strokes.
+pub const BLACK: Color = [0, 0, 0];
+
+/// Page size in points.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PageSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PageSize {
+    /// ISO A4 (210 x 297 mm)
+    pub const A4: PageSize = PageSize {
+        width: 595.0,
+        height: 842.0,
+    };
+    /// US Letter (8.5 x 11 in)
+    pub const LETTER: PageSize = PageSize {
+        width: 612.0,
+        height: 792.0,
+    };
+}
+
+impl Default for PageSize {
+    fn default() -> Self {
+        PageSize::A4
+    }
+}
+
+/// One thing drawn on a page. Coordinates are in points from the page's
+/// top-left corner.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Item {
+    /// A run of text with its baseline starting at (`x`, `y`)
+    Text {
+        x: f32,
+        y: f32,
+        text: String,
+        #[serde(default = "default_font_size")]
+        size: f32,
+        #[serde(default)]
+        bold: bool,
+        #[serde(default = "default_color")]
+        color: Color,
+    },
+    /// A straight line
+    Line {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        #[serde(default = "default_line_width")]
+        width: f32,
+        #[serde(default = "default_color")]
+        color: Color,
+    },
+    /// A rectangle, filled, stroked or both
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        #[serde(default)]
+        fill: Option<Color>,
+        #[serde(default)]
+        stroke: Option<Color>,
+        #[serde(default = "default_line_width")]
+        line_width: f32,
+    },
+}
+
+fn default_font_size() -> f32 {
+    12.0
+}
+
+fn default_line_width() -> f32 {
+    1.0
+}
+
+fn default_color() -> Color {
+    BLACK
+}
+
+/// One page of a paginated document.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Page {
+    #[serde(default)]
+    pub items: Vec<Item>,
+}
+
+/// What a print job prints.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Document {
+    /// Vector pages, all of one size
+    Pages {
+        #[serde(default)]
+        page_size: PageSize,
+        pages: Vec<Page>,
+    },
+    /// HTML laid out by the browser
+    Html { html: String },
+}
+
+impl Document {
+    /// Number of pages, if the document is paginated.
+    pub fn page_count(&self) -> Option<u32> {
+        match self {
+            Document::Pages { pages, .. } => Some(pages.len() as u32),
+            Document::Html { .. } => None,
+        }
+    }
+
+    /// Check the document against the limits.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Document::Html { html } => {
+                if html.is_empty() {
+                    return Err(String::from("HTML document is empty"));
+                }
+                if html.len() > MAX_HTML_LEN {
+                    return Err(format!("HTML document exceeds {} bytes", MAX_HTML_LEN));
+                }
+                Ok(())
+            }
+            Document::Pages { page_size, pages } => {
+                let (min, max) = PAGE_EDGE_RANGE;
+                let edge_ok = |edge: f32| edge.is_finite() && (min..=max).contains(&edge);
+                if !edge_ok(page_size.width) || !edge_ok(page_size.height) {
+                    return Err(format!(
+                        "Page size must be between {} and {} points",
+                        min, max
+                    ));
+                }
+                if pages.is_empty() {
+                    return Err(String::from("Document has no pages"));
+                }
+                if pages.len() > MAX_PAGES {
+                    return Err(format!("Document exceeds {} pages", MAX_PAGES));
+                }
+                let mut total = 0;
+                for (index, page) in pages.iter().enumerate() {
+                    if page.items.len() > MAX_ITEMS_PER_PAGE {
+                        return Err(format!(
+                            "Page {} exceeds {} items",
+                            index + 1,
+                            MAX_ITEMS_PER_PAGE
+                        ));
+                    }
+                    total += page.items.len();
+                    for item in &page.items {
+                        validate_item(item).map_err(|e| format!("Page {}: {}", index + 1, e))?;
+                    }
+                }
+                if total > MAX_ITEMS {
+                    return Err(format!("Document exceeds {} items", MAX_ITEMS));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Check one item's numbers and text.
+fn validate_item(item: &Item) -> Result<(), String> {
+    let numbers: &[f32] = match item {
+        Item::Text {
+            x, y, text, size, ..
+        } => {
+            if text.len() > MAX_TEXT_LEN {
+                return Err(format!("Text run exceeds {} bytes", MAX_TEXT_LEN));
+            }
+            if !(*size > 0.0 && *size <= MAX_FONT_SIZE) {
+                return Err(format!(
+                    "Font size must be above 0 and at most {}",
+                    MAX_FONT_SIZE
+                ));
+            }
+            &[*x, *y]
+        }
+        Item::Line {
+            x1,
+            y1,
+            x2,
+            y2,
+            width,
+            ..
+        } => &[*x1, *y1, *x2, *y2, *width],
+        Item::Rect {
+            x,
+            y,
+            width,
+            height,
+            line_width,
+            ..
+        } => &[*x, *y, *width, *height, *line_width],
+    };
+    if numbers.iter().any(|n| !n.is_finite()) {
+        return Err(String::from("Coordinates must be finite numbers"));
+    }
+    Ok(())
+}
+
+/// File name for a job's PDF: the title with path separators and control
+/// characters replaced, or "Untitled".
+pub fn file_stem(title: &str) -> String {
+    let stem: String = title
+        .trim()
+        .chars()
+        .map(|c| {
+            if c == '/' || c == '\\' || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let stem = stem.trim_start_matches('.');
+    if stem.is_empty() {
+        String::from("Untitled")
+    } else {
+        String::from(stem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn text(text: &str) -> Item {
+        Item::Text {
+            x: 72.0,
+            y: 72.0,
+            text: String::from(text),
+            size: 12.0,
+            bold: false,
+            color: BLACK,
+        }
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let document: Document = serde_json::from_str(
+            r#"{"kind":"pages","pages":[{"items":[
+                {"type":"text","x":10,"y":20,"text":"Hi"},
+                {"type":"rect","x":0,"y":0,"width":5,"height":5,"fill":[255,0,0]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let Document::Pages { page_size, pages } = &document else {
+            panic!("expected pages");
+        };
+        assert_eq!(*page_size, PageSize::A4);
+        assert_eq!(
+            pages[0].items[0],
+            Item::Text {
+                x: 10.0,
+                y: 20.0,
+                text: String::from("Hi"),
+                size: 12.0,
+                bold: false,
+                color: BLACK,
+            }
+        );
+        assert!(matches!(
+            pages[0].items[1],
+            Item::Rect {
+                fill: Some([255, 0, 0]),
+                stroke: None,
+                ..
+            }
+        ));
+        assert_eq!(document.page_count(), Some(1));
+        assert!(document.validate().is_ok());
+
+        let html: Document = serde_json::from_str(r#"{"kind":"html","html":"<p>Hi</p>"}"#).unwrap();
+        assert_eq!(html.page_count(), None);
+        assert!(html.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_limits() {
+        let pages = |pages: Vec<Page>| Document::Pages {
+            page_size: PageSize::LETTER,
+            pages,
+        };
+        assert!(pages(Vec::new()).validate().is_err());
+        assert!(pages(vec![Page::default(); MAX_PAGES + 1])
+            .validate()
+            .is_err());
+        assert!(pages(vec![Page {
+            items: vec![text("a"); MAX_ITEMS_PER_PAGE + 1]
+        }])
+        .validate()
+        .is_err());
+        let long = "a".repeat(MAX_TEXT_LEN + 1);
+        assert!(pages(vec![Page {
+            items: vec![text(&long)]
+        }])
+        .validate()
+        .is_err());
+
+        let mut item = text("a");
+        if let Item::Text { x, .. } = &mut item {
+            *x = f32::NAN;
+        }
+        assert!(pages(vec![Page { items: vec![item] }]).validate().is_err());
+
+        let tiny = Document::Pages {
+            page_size: PageSize {
+                width: 10.0,
+                height: 10.0,
+            },
+            pages: vec![Page::default()],
+        };
+        assert!(tiny.validate().is_err());
+        assert!(Document::Html {
+            html: String::new()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(file_stem("Invoice 42"), "Invoice 42");
+        assert_eq!(file_stem("a/b\\c\n"), "a_b_c");
+        assert_eq!(file_stem("../secret"), "_secret");
+        assert_eq!(file_stem("  "), "Untitled");
+        assert_eq!(file_stem("..."), "Untitled");
+    }
+}
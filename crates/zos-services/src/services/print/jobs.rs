@@ -0,0 +1,341 @@
+//! Print job table
+//!
+//! Jobs for the print dialog are shown one at a time, oldest first: the
+//! browser can only show one dialog, and it blocks the page while open. A
+//! job waits as `queued` until the dialog is free, is `printing` while its
+//! dialog is open, and ends `completed`, `cancelled` or `failed` when the
+//! supervisor reports the dialog closed.
+//!
+//! PDF jobs are rendered when they are submitted and are `printing` until
+//! the file is written.
+//!
+//! Finished jobs are kept for status queries until [`MAX_FINISHED_JOBS`]
+//! newer ones have finished.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use super::document::Document;
+
+/// Maximum unfinished jobs (DoS protection per Rule 11).
+pub const MAX_ACTIVE_JOBS: usize = 32;
+
+/// Maximum unfinished jobs of one process.
+pub const MAX_JOBS_PER_PID: usize = 8;
+
+/// Finished jobs kept for status queries.
+pub const MAX_FINISHED_JOBS: usize = 64;
+
+/// Where a job goes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    /// The supervisor's print dialog
+    #[default]
+    Dialog,
+    /// A PDF in the user's Documents directory
+    Pdf,
+}
+
+/// Where a job is up to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Waiting for the print dialog
+    Queued,
+    /// Dialog open, or PDF being written
+    Printing,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Cancelled | JobStatus::Failed
+        )
+    }
+}
+
+/// A print job as reported to apps and on the event bus.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PrintJob {
+    pub id: u32,
+    /// Submitting process
+    pub pid: u32,
+    pub title: String,
+    pub target: Target,
+    pub status: JobStatus,
+    /// Page count, for paginated documents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages: Option<u32>,
+    /// Wall-clock time of submission (ms)
+    pub submitted_at: u64,
+    /// The written PDF
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// How a job ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Printed, or written to `path`
+    Completed {
+        path: Option<String>,
+    },
+    Cancelled,
+    Failed(String),
+}
+
+/// All jobs, and the documents of those waiting for the dialog.
+#[derive(Debug)]
+pub struct JobTable {
+    jobs: BTreeMap<u32, PrintJob>,
+    documents: BTreeMap<u32, Document>,
+    /// Job whose dialog is open
+    dialog: Option<u32>,
+    next_id: u32,
+}
+
+impl Default for JobTable {
+    fn default() -> Self {
+        Self {
+            jobs: BTreeMap::new(),
+            documents: BTreeMap::new(),
+            dialog: None,
+            next_id: 1,
+        }
+    }
+}
+
+impl JobTable {
+    /// Add a job. Dialog jobs keep their document until they are shown;
+    /// PDF jobs start `printing` right away.
+    pub fn submit(
+        &mut self,
+        pid: u32,
+        title: String,
+        target: Target,
+        document: Document,
+        now_ms: u64,
+    ) -> Result<PrintJob, String> {
+        let active: Vec<&PrintJob> = self
+            .jobs
+            .values()
+            .filter(|job| !job.status.is_finished())
+            .collect();
+        if active.len() >= MAX_ACTIVE_JOBS {
+            return Err(String::from("Print queue full"));
+        }
+        if active.iter().filter(|job| job.pid == pid).count() >= MAX_JOBS_PER_PID {
+            return Err(format!(
+                "Too many unfinished print jobs (max {})",
+                MAX_JOBS_PER_PID
+            ));
+        }
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        let job = PrintJob {
+            id,
+            pid,
+            title,
+            target,
+            status: match target {
+                Target::Dialog => JobStatus::Queued,
+                Target::Pdf => JobStatus::Printing,
+            },
+            pages: document.page_count(),
+            submitted_at: now_ms,
+            path: None,
+            error: None,
+        };
+        if target == Target::Dialog {
+            self.documents.insert(id, document);
+        }
+        self.jobs.insert(id, job.clone());
+        Ok(job)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&PrintJob> {
+        self.jobs.get(&id)
+    }
+
+    /// Jobs of `pid`, oldest first.
+    pub fn jobs_of(&self, pid: u32) -> Vec<PrintJob> {
+        self.jobs
+            .values()
+            .filter(|job| job.pid == pid)
+            .cloned()
+            .collect()
+    }
+
+    /// Open the dialog for the oldest queued job, if it is free.
+    pub fn next_dialog(&mut self) -> Option<(PrintJob, Document)> {
+        if self.dialog.is_some() {
+            return None;
+        }
+        let id = *self.documents.keys().next()?;
+        let document = self.documents.remove(&id)?;
+        let job = self.jobs.get_mut(&id)?;
+        job.status = JobStatus::Printing;
+        self.dialog = Some(id);
+        Some((job.clone(), document))
+    }
+
+    /// Record how an unfinished job ended.
+    pub fn finish(&mut self, id: u32, outcome: Outcome) -> Option<PrintJob> {
+        let job = self
+            .jobs
+            .get_mut(&id)
+            .filter(|job| !job.status.is_finished())?;
+        match outcome {
+            Outcome::Completed { path } => {
+                job.status = JobStatus::Completed;
+                job.path = path;
+            }
+            Outcome::Cancelled => job.status = JobStatus::Cancelled,
+            Outcome::Failed(error) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(error);
+            }
+        }
+        let job = job.clone();
+        self.documents.remove(&id);
+        if self.dialog == Some(id) {
+            self.dialog = None;
+        }
+        self.prune();
+        Some(job)
+    }
+
+    /// Cancel a job of `pid` that is still queued.
+    pub fn cancel(&mut self, pid: u32, id: u32) -> Result<PrintJob, String> {
+        match self.jobs.get(&id) {
+            Some(job) if job.pid != pid => Err(String::from("No such print job")),
+            Some(job) if job.status != JobStatus::Queued => {
+                Err(format!("Print job {} has already started", id))
+            }
+            Some(_) => self
+                .finish(id, Outcome::Cancelled)
+                .ok_or_else(|| String::from("No such print job")),
+            None => Err(String::from("No such print job")),
+        }
+    }
+
+    /// Forget the oldest finished jobs beyond [`MAX_FINISHED_JOBS`].
+    fn prune(&mut self) {
+        let finished: Vec<u32> = self
+            .jobs
+            .values()
+            .filter(|job| job.status.is_finished())
+            .map(|job| job.id)
+            .collect();
+        let excess = finished.len().saturating_sub(MAX_FINISHED_JOBS);
+        for id in finished.into_iter().take(excess) {
+            self.jobs.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn html() -> Document {
+        Document::Html {
+            html: String::from("<p>Hi</p>"),
+        }
+    }
+
+    fn submit(table: &mut JobTable, pid: u32, target: Target) -> u32 {
+        table
+            .submit(pid, String::from("Doc"), target, html(), 5)
+            .unwrap()
+            .id
+    }
+
+    #[test]
+    fn test_dialog_one_at_a_time() {
+        let mut table = JobTable::default();
+        let first = submit(&mut table, 10, Target::Dialog);
+        let second = submit(&mut table, 11, Target::Dialog);
+        assert_eq!(table.get(first).unwrap().status, JobStatus::Queued);
+
+        let (job, document) = table.next_dialog().unwrap();
+        assert_eq!(job.id, first);
+        assert_eq!(job.status, JobStatus::Printing);
+        assert_eq!(document, html());
+        assert!(table.next_dialog().is_none());
+
+        let done = table
+            .finish(first, Outcome::Completed { path: None })
+            .unwrap();
+        assert_eq!(done.status, JobStatus::Completed);
+        // A late second report changes nothing
+        assert!(table.finish(first, Outcome::Cancelled).is_none());
+        assert_eq!(table.next_dialog().unwrap().0.id, second);
+    }
+
+    #[test]
+    fn test_pdf_jobs_skip_the_dialog() {
+        let mut table = JobTable::default();
+        let id = submit(&mut table, 10, Target::Pdf);
+        assert_eq!(table.get(id).unwrap().status, JobStatus::Printing);
+        assert!(table.next_dialog().is_none());
+
+        let path = Some(String::from("/home/1/Documents/Doc.pdf"));
+        let job = table
+            .finish(id, Outcome::Completed { path: path.clone() })
+            .unwrap();
+        assert_eq!(job.path, path);
+    }
+
+    #[test]
+    fn test_cancel_only_own_queued_jobs() {
+        let mut table = JobTable::default();
+        let shown = submit(&mut table, 10, Target::Dialog);
+        let queued = submit(&mut table, 10, Target::Dialog);
+        table.next_dialog();
+
+        assert!(table.cancel(11, queued).is_err());
+        assert!(table.cancel(10, shown).is_err());
+        assert!(table.cancel(10, 99).is_err());
+        assert_eq!(
+            table.cancel(10, queued).unwrap().status,
+            JobStatus::Cancelled
+        );
+        assert_eq!(table.jobs_of(10).len(), 2);
+        assert!(table.jobs_of(11).is_empty());
+    }
+
+    #[test]
+    fn test_limits_and_pruning() {
+        let mut table = JobTable::default();
+        for _ in 0..MAX_JOBS_PER_PID {
+            submit(&mut table, 10, Target::Dialog);
+        }
+        assert!(table
+            .submit(10, String::from("Doc"), Target::Dialog, html(), 5)
+            .is_err());
+
+        let mut table = JobTable::default();
+        let ids: Vec<u32> = (0..MAX_FINISHED_JOBS + 2)
+            .map(|i| {
+                let id = submit(&mut table, 10 + i as u32, Target::Pdf);
+                table.finish(id, Outcome::Failed(String::from("full")));
+                id
+            })
+            .collect();
+        assert!(table.get(ids[0]).is_none());
+        assert!(table.get(ids[1]).is_none());
+        assert_eq!(table.get(ids[2]).unwrap().error, Some(String::from("full")));
+    }
+}
@@ -0,0 +1,710 @@
+//! Print Service
+//!
+//! The PrintService takes print jobs from apps and either:
+//! - Queues them for the browser's print dialog, which the supervisor opens
+//!   one job at a time (see [`jobs`]), or
+//! - Renders them to PDF (see [`pdf`]) and saves the file in the user's
+//!   `Documents` directory
+//!
+//! Every change of a job's status is published on the event bus under
+//! `print/job`.
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - SUBMIT: Document checked AND job recorded (dialog: queued; PDF:
+//!   rendered AND name lookup started)
+//! - STATUS: Jobs of the caller read
+//! - CANCEL: Queued job of the caller marked cancelled AND its document dropped
+//!
+//! **Acceptable partial failure:**
+//! - Supervisor without a print callback → dialog jobs end `failed`
+//! - PDF write fails → job ends `failed` with the VFS error
+//!
+//! **Forbidden:**
+//! - Processes seeing or cancelling other processes' jobs
+//! - Overwriting an existing file with a PDF
+//! - Writing anywhere but `/home/{user_id}/Documents`
+//! - Unbounded job, document or pending request growth (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_PRINT_SUBMIT (0x8A00)`: Submit a job for the dialog or as a PDF
+//! - `MSG_PRINT_STATUS (0x8A02)`: Get the caller's jobs
+//! - `MSG_PRINT_CANCEL (0x8A04)`: Cancel a job that hasn't started
+//! - `MSG_PRINT_DIALOG_DONE (0x8A06)`: Supervisor reports the dialog closed
+//!
+//! Dialog jobs are sent to the supervisor as `PRINT:DIALOG:{hex_json}` on
+//! the debug channel.
+//!
+//! # Storage Access
+//!
+//! This service uses VFS IPC (async/await pattern, see
+//! [`zos_vfs::client::futures`]) to save PDFs. All storage
+//! operations flow through the VFS service per Invariant 31. Files are
+//! written with the permissions of the session user, so a PDF for anyone
+//! else is refused by the VFS.
+
+extern crate alloc;
+
+pub mod document;
+pub mod jobs;
+pub mod pdf;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+use zos_ipc::events;
+use zos_vfs::client::futures::{Vfs, VfsExecutor};
+
+use crate::manifests::PRINT_MANIFEST;
+use crate::response::JsonResponder;
+
+pub use document::{Document, Item, Page, PageSize};
+pub use jobs::{JobStatus, JobTable, Outcome, PrintJob, Target};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for print service - re-exported from zos-ipc.
+pub mod print_msg {
+    pub use zos_ipc::print::*;
+}
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Maximum PDF saves in progress. Each holds a rendered PDF, so this is
+/// kept low (DoS protection per Rule 11).
+const MAX_SAVES: usize = 4;
+
+/// Largest PDF that can be saved (the VFS content limit).
+const MAX_PDF_SIZE: usize = 16 * 1024 * 1024;
+
+/// Names tried for a PDF: "{title}.pdf", then "{title} (2).pdf" and so on.
+const MAX_NAME_ATTEMPTS: u32 = 20;
+
+/// PIDs allowed to report dialogs done.
+/// The supervisor's messages arrive through Init.
+const TRUSTED_PIDS_FOR_DONE: &[u32] = &[0, 1];
+
+// =============================================================================
+// Request Types
+// =============================================================================
+
+/// Payload of MSG_PRINT_SUBMIT.
+#[derive(Clone, Debug, Deserialize)]
+struct SubmitRequest {
+    title: String,
+    #[serde(default)]
+    target: Target,
+    /// Owner of the Documents directory a PDF goes to (hex)
+    #[serde(default)]
+    user_id: Option<String>,
+    document: Document,
+}
+
+/// Payload of MSG_PRINT_STATUS.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct StatusRequest {
+    #[serde(default)]
+    id: Option<u32>,
+}
+
+/// Payload of MSG_PRINT_CANCEL.
+#[derive(Clone, Debug, Deserialize)]
+struct CancelRequest {
+    id: u32,
+}
+
+/// How the print dialog closed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DialogOutcome {
+    Printed,
+    Cancelled,
+    Failed,
+}
+
+/// Payload of MSG_PRINT_DIALOG_DONE.
+#[derive(Clone, Debug, Deserialize)]
+struct DialogDone {
+    id: u32,
+    outcome: DialogOutcome,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+// =============================================================================
+// PDF Saving
+// =============================================================================
+
+/// A finished PDF save: the job and the path written, or why it failed.
+type Saved = (u32, Result<String, String>);
+
+/// Path of the `attempt`th name tried for a PDF.
+fn pdf_path(dir: &str, stem: &str, attempt: u32) -> String {
+    if attempt <= 1 {
+        format!("{}/{}.pdf", dir, stem)
+    } else {
+        format!("{}/{} ({}).pdf", dir, stem, attempt)
+    }
+}
+
+/// Write a PDF under the first free name in `dir`.
+async fn save_pdf(vfs: Vfs, dir: String, stem: String, pdf: Vec<u8>) -> Result<String, String> {
+    for attempt in 1..=MAX_NAME_ATTEMPTS {
+        let path = pdf_path(&dir, &stem, attempt);
+        if vfs.exists(&path).await? {
+            continue;
+        }
+        return match vfs.write(&path, &pdf).await {
+            Ok(()) => Ok(path),
+            Err(e) => Err(format!("Failed to write {}: {}", path, e)),
+        };
+    }
+    Err(format!("No free file name for {:?} in {}", stem, dir))
+}
+
+// =============================================================================
+// PrintService Application
+// =============================================================================
+
+/// PrintService - print dialog queue and PDF export
+pub struct PrintService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// All jobs
+    jobs: JobTable,
+    /// PDF saves in progress
+    saves: VfsExecutor<Saved>,
+}
+
+impl Default for PrintService {
+    fn default() -> Self {
+        Self {
+            registered: false,
+            jobs: JobTable::default(),
+            saves: VfsExecutor::new(),
+        }
+    }
+}
+
+impl PrintService {
+    /// Check and enforce the limit on saves in progress (DoS protection per
+    /// Rule 11).
+    fn check_save_limit(&self) -> bool {
+        if self.saves.running() >= MAX_SAVES {
+            syscall::debug(&format!(
+                "PrintService: PDF save limit reached ({}/{})",
+                self.saves.running(),
+                MAX_SAVES
+            ));
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Record the outcome of every finished save.
+    fn finish_saves(&mut self) {
+        while let Some((job_id, result)) = self.saves.next_finished() {
+            let outcome = match result {
+                Ok(path) => Outcome::Completed { path: Some(path) },
+                Err(e) => Outcome::Failed(e),
+            };
+            self.finish(job_id, outcome);
+        }
+    }
+
+    /// Record how a job ended, publish it and show the next dialog.
+    fn finish(&mut self, job_id: u32, outcome: Outcome) {
+        if let Some(job) = self.jobs.finish(job_id, outcome) {
+            syscall::debug(&format!("PrintService: Job {} {:?}", job.id, job.status));
+            self.publish(&job);
+        }
+        self.pump_dialog();
+    }
+
+    /// Hand the next queued job to the supervisor if the dialog is free.
+    fn pump_dialog(&mut self) {
+        let Some((job, document)) = self.jobs.next_dialog() else {
+            return;
+        };
+        self.publish(&job);
+        let json = serde_json::to_vec(&serde_json::json!({
+            "id": job.id,
+            "pid": job.pid,
+            "title": job.title,
+            "document": document,
+        }))
+        .unwrap_or_default();
+        let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+        syscall::debug(&format!("{}{}", zos_ipc::debug::PRINT_DIALOG, hex));
+    }
+
+    /// Publish a job's status on the event bus.
+    fn publish(&self, job: &PrintJob) {
+        let json = serde_json::to_vec(&serde_json::json!({ "topic": "print/job", "payload": job }))
+            .unwrap_or_default();
+        if let Err(e) = syscall::send_named("events", events::MSG_EVENT_PUBLISH, &json) {
+            syscall::debug(&format!("PrintService: Publish failed: {}", e));
+        }
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_PRINT_SUBMIT
+    fn handle_submit(&mut self, msg: &Message, now_ms: u64) -> Result<(), AppError> {
+        let tag = print_msg::MSG_PRINT_SUBMIT_RESPONSE;
+        let request: SubmitRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(e) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    &format!("Invalid print request: {}", e),
+                );
+            }
+        };
+        match self.submit(msg.from_pid, request, now_ms) {
+            Ok(job) => {
+                let json = serde_json::to_vec(&job).unwrap_or_default();
+                self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Check a request, record the job and start it.
+    fn submit(
+        &mut self,
+        pid: u32,
+        request: SubmitRequest,
+        now_ms: u64,
+    ) -> Result<PrintJob, String> {
+        if request.title.len() > document::MAX_TITLE_LEN {
+            return Err(format!("Title exceeds {} bytes", document::MAX_TITLE_LEN));
+        }
+        request.document.validate()?;
+
+        if request.target == Target::Dialog {
+            let job =
+                self.jobs
+                    .submit(pid, request.title, Target::Dialog, request.document, now_ms)?;
+            self.publish(&job);
+            self.pump_dialog();
+            // The dialog may have opened for it already
+            return Ok(self.jobs.get(job.id).cloned().unwrap_or(job));
+        }
+
+        let Document::Pages { page_size, pages } = &request.document else {
+            return Err(String::from("Only paginated documents can be saved as PDF"));
+        };
+        let user_id = request
+            .user_id
+            .as_deref()
+            .and_then(|hex| u128::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| String::from("PDF export requires a hex user_id"))?;
+        if !self.check_save_limit() {
+            return Err(String::from("Too many PDFs being saved, try again later"));
+        }
+
+        let pdf = pdf::render(&request.title, *page_size, pages);
+        let stem = document::file_stem(&request.title);
+        let job = self
+            .jobs
+            .submit(pid, request.title, Target::Pdf, request.document, now_ms)?;
+        self.publish(&job);
+
+        if pdf.len() > MAX_PDF_SIZE {
+            self.finish(
+                job.id,
+                Outcome::Failed(format!("PDF exceeds {} bytes", MAX_PDF_SIZE)),
+            );
+        } else {
+            let job_id = job.id;
+            let save = save_pdf(
+                self.saves.handle(),
+                format!("/home/{}/Documents", user_id),
+                stem,
+                pdf,
+            );
+            self.saves.spawn(async move { (job_id, save.await) });
+            // Fails right away if the VFS can't be reached
+            self.finish_saves();
+        }
+        Ok(self.jobs.get(job.id).cloned().unwrap_or(job))
+    }
+
+    /// Handle MSG_PRINT_STATUS
+    fn handle_status(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = print_msg::MSG_PRINT_STATUS_RESPONSE;
+        let request: StatusRequest = if msg.data.is_empty() {
+            StatusRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_error_response(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid status request: expected {\"id\": u32?}",
+                    );
+                }
+            }
+        };
+
+        let mut jobs = self.jobs.jobs_of(msg.from_pid);
+        if let Some(id) = request.id {
+            jobs.retain(|job| job.id == id);
+            if jobs.is_empty() {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "No such print job",
+                );
+            }
+        }
+        let json = serde_json::to_vec(&serde_json::json!({ "jobs": jobs })).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_PRINT_CANCEL
+    fn handle_cancel(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = print_msg::MSG_PRINT_CANCEL_RESPONSE;
+        let request: CancelRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid cancel request: expected {\"id\": u32}",
+                );
+            }
+        };
+        match self.jobs.cancel(msg.from_pid, request.id) {
+            Ok(job) => {
+                self.publish(&job);
+                let json = serde_json::to_vec(&job).unwrap_or_default();
+                self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+            }
+            Err(e) => self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_PRINT_DIALOG_DONE
+    fn handle_dialog_done(&mut self, msg: &Message) -> Result<(), AppError> {
+        if !TRUSTED_PIDS_FOR_DONE.contains(&msg.from_pid) {
+            syscall::debug(&format!(
+                "PrintService: SECURITY - DIALOG_DONE from non-system PID {}",
+                msg.from_pid
+            ));
+            return Ok(());
+        }
+        let Ok(done) = serde_json::from_slice::<DialogDone>(&msg.data) else {
+            syscall::debug("PrintService: Invalid DIALOG_DONE payload");
+            return Ok(());
+        };
+        let outcome = match done.outcome {
+            DialogOutcome::Printed => Outcome::Completed { path: None },
+            DialogOutcome::Cancelled => Outcome::Cancelled,
+            DialogOutcome::Failed => Outcome::Failed(
+                done.error
+                    .unwrap_or_else(|| String::from("Print dialog failed")),
+            ),
+        };
+        self.finish(done.id, outcome);
+        Ok(())
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Handle a VFS response to a PDF save. Returns false if the message
+    /// isn't one.
+    fn handle_vfs_response(&mut self, msg: &Message) -> bool {
+        if !self.saves.on_message(msg.tag, &msg.data) {
+            return false;
+        }
+        self.finish_saves();
+        true
+    }
+}
+
+impl JsonResponder for PrintService {
+    const SERVICE_NAME: &'static str = "PrintService";
+}
+
+impl ZeroApp for PrintService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &PRINT_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("PrintService starting (PID {})", ctx.pid));
+
+        // Register with init as "print" service
+        let service_name = "print";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("PrintService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        // VFS responses (Invariant 31 compliant - storage via VFS IPC)
+        if self.handle_vfs_response(&msg) {
+            return Ok(());
+        }
+
+        match msg.tag {
+            events::MSG_EVENT_PUBLISH_RESPONSE => Ok(()),
+
+            // Print service protocol
+            print_msg::MSG_PRINT_SUBMIT => self.handle_submit(&msg, ctx.wallclock_ms),
+            print_msg::MSG_PRINT_STATUS => self.handle_status(&msg),
+            print_msg::MSG_PRINT_CANCEL => self.handle_cancel(&msg),
+            print_msg::MSG_PRINT_DIALOG_DONE => self.handle_dialog_done(&msg),
+
+            _ => {
+                syscall::debug(&format!(
+                    "PrintService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("PrintService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+    use zos_vfs::ipc::vfs_msg;
+
+    const PAGES: &str =
+        r#"{"kind":"pages","pages":[{"items":[{"type":"text","x":72,"y":72,"text":"Hi"}]}]}"#;
+
+    fn submit(service: &mut PrintService, pid: u32, target: &str, document: &str) {
+        let json = format!(
+            r#"{{"title":"Report","target":"{}","user_id":"2a","document":{}}}"#,
+            target, document
+        );
+        let msg = mock_message(print_msg::MSG_PRINT_SUBMIT, pid, json.into_bytes());
+        service.handle_submit(&msg, 1_000).unwrap();
+    }
+
+    fn dialog_done(service: &mut PrintService, from_pid: u32, json: &str) {
+        let msg = mock_message(
+            print_msg::MSG_PRINT_DIALOG_DONE,
+            from_pid,
+            json.as_bytes().to_vec(),
+        );
+        service.handle_dialog_done(&msg).unwrap();
+    }
+
+    fn status(service: &PrintService, id: u32) -> JobStatus {
+        service.jobs.get(id).unwrap().status
+    }
+
+    #[test]
+    fn test_pdf_paths() {
+        assert_eq!(
+            pdf_path("/home/42/Documents", "Report", 1),
+            "/home/42/Documents/Report.pdf"
+        );
+        assert_eq!(
+            pdf_path("/home/42/Documents", "Report", 3),
+            "/home/42/Documents/Report (3).pdf"
+        );
+    }
+
+    #[test]
+    fn test_dialog_jobs_queue_and_finish() {
+        let mut service = PrintService::default();
+        submit(&mut service, 10, "dialog", PAGES);
+        submit(
+            &mut service,
+            11,
+            "dialog",
+            r#"{"kind":"html","html":"<p>Hi</p>"}"#,
+        );
+        assert_eq!(status(&service, 1), JobStatus::Printing);
+        assert_eq!(status(&service, 2), JobStatus::Queued);
+
+        // Only the supervisor (via Init) can report the dialog closed
+        dialog_done(&mut service, 10, r#"{"id":1,"outcome":"printed"}"#);
+        assert_eq!(status(&service, 1), JobStatus::Printing);
+
+        dialog_done(&mut service, 1, r#"{"id":1,"outcome":"printed"}"#);
+        assert_eq!(status(&service, 1), JobStatus::Completed);
+        assert_eq!(status(&service, 2), JobStatus::Printing);
+
+        dialog_done(
+            &mut service,
+            1,
+            r#"{"id":2,"outcome":"failed","error":"No printer"}"#,
+        );
+        let job = service.jobs.get(2).unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.error.as_deref(), Some("No printer"));
+    }
+
+    #[test]
+    fn test_pdf_job_finds_free_name_and_writes() {
+        let mut service = PrintService::default();
+        submit(&mut service, 10, "pdf", PAGES);
+        assert_eq!(status(&service, 1), JobStatus::Printing);
+        assert_eq!(service.saves.in_flight(), 1);
+
+        // Not a response the save waits for
+        let stray = mock_message(vfs_msg::MSG_VFS_READ_RESPONSE, 4, b"{}".to_vec());
+        assert!(!service.handle_vfs_response(&stray));
+
+        // "Report.pdf" is taken, "Report (2).pdf" is free
+        for (tag, json) in [
+            (
+                vfs_msg::MSG_VFS_EXISTS_RESPONSE,
+                r#"{"result":{"Ok":true}}"#,
+            ),
+            (
+                vfs_msg::MSG_VFS_EXISTS_RESPONSE,
+                r#"{"result":{"Ok":false}}"#,
+            ),
+        ] {
+            let msg = mock_message(tag, 4, json.as_bytes().to_vec());
+            assert!(service.handle_vfs_response(&msg));
+            assert_eq!(status(&service, 1), JobStatus::Printing);
+        }
+
+        let written = mock_message(
+            vfs_msg::MSG_VFS_WRITE_RESPONSE,
+            4,
+            br#"{"result":{"Ok":null}}"#.to_vec(),
+        );
+        assert!(service.handle_vfs_response(&written));
+        let job = service.jobs.get(1).unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(
+            job.path.as_deref(),
+            Some("/home/42/Documents/Report (2).pdf")
+        );
+        assert_eq!(service.saves.running(), 0);
+    }
+
+    #[test]
+    fn test_pdf_write_failure_fails_job() {
+        let mut service = PrintService::default();
+        submit(&mut service, 10, "pdf", PAGES);
+        for (tag, json) in [
+            (
+                vfs_msg::MSG_VFS_EXISTS_RESPONSE,
+                r#"{"result":{"Ok":false}}"#,
+            ),
+            (
+                vfs_msg::MSG_VFS_WRITE_RESPONSE,
+                r#"{"result":{"Err":"NotFound"}}"#,
+            ),
+        ] {
+            let msg = mock_message(tag, 4, json.as_bytes().to_vec());
+            assert!(service.handle_vfs_response(&msg));
+        }
+        let job = service.jobs.get(1).unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("Failed to write /home/42/Documents/Report.pdf"));
+    }
+
+    #[test]
+    fn test_rejected_submits() {
+        let mut service = PrintService::default();
+        // HTML can't be saved as PDF
+        submit(
+            &mut service,
+            10,
+            "pdf",
+            r#"{"kind":"html","html":"<p>Hi</p>"}"#,
+        );
+        // PDF needs a user
+        let msg = mock_message(
+            print_msg::MSG_PRINT_SUBMIT,
+            10,
+            format!(
+                r#"{{"title":"Report","target":"pdf","document":{}}}"#,
+                PAGES
+            )
+            .into_bytes(),
+        );
+        service.handle_submit(&msg, 1_000).unwrap();
+        // Documents need pages
+        submit(&mut service, 10, "dialog", r#"{"kind":"pages","pages":[]}"#);
+
+        assert!(service.jobs.jobs_of(10).is_empty());
+        assert_eq!(service.saves.running(), 0);
+    }
+
+    #[test]
+    fn test_cancel_only_own_queued_jobs() {
+        let mut service = PrintService::default();
+        submit(&mut service, 10, "dialog", PAGES);
+        submit(&mut service, 10, "dialog", PAGES);
+
+        let cancel = |pid, id: u32| {
+            mock_message(
+                print_msg::MSG_PRINT_CANCEL,
+                pid,
+                format!(r#"{{"id":{}}}"#, id).into_bytes(),
+            )
+        };
+        service.handle_cancel(&cancel(11, 2)).unwrap();
+        service.handle_cancel(&cancel(10, 1)).unwrap();
+        assert_eq!(status(&service, 1), JobStatus::Printing);
+        assert_eq!(status(&service, 2), JobStatus::Queued);
+
+        service.handle_cancel(&cancel(10, 2)).unwrap();
+        assert_eq!(status(&service, 2), JobStatus::Cancelled);
+    }
+}
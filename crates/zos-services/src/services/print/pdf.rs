@@ -0,0 +1,340 @@
+//! PDF rendering
+//!
+//! Renders paginated documents to PDF 1.4 with nothing but the standard
+//! Helvetica fonts, so no font data is embedded and the output is small.
+//! Text is encoded as WinAnsi (Latin-1 for the characters that matter);
+//! characters outside it are printed as `?`.
+//!
+//! Items are positioned from the page's top-left corner, as apps lay them
+//! out; PDF measures from the bottom-left, so every `y` is flipped.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use super::document::{Color, Item, Page, PageSize};
+
+/// Object numbers fixed by the layout: the first page is object
+/// `FIRST_PAGE_OBJECT`, its content stream the next, and so on.
+const CATALOG_OBJECT: usize = 1;
+const PAGES_OBJECT: usize = 2;
+const FONT_OBJECT: usize = 3;
+const BOLD_FONT_OBJECT: usize = 4;
+const INFO_OBJECT: usize = 5;
+const FIRST_PAGE_OBJECT: usize = 6;
+
+/// Render `pages` to a PDF file titled `title`.
+pub fn render(title: &str, page_size: PageSize, pages: &[Page]) -> Vec<u8> {
+    let mut writer = PdfWriter::default();
+    writer.raw("%PDF-1.4\n%\u{e2}\u{e3}\u{cf}\u{d3}\n");
+
+    writer.object(
+        CATALOG_OBJECT,
+        &format!("<< /Type /Catalog /Pages {} 0 R >>", PAGES_OBJECT),
+    );
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", FIRST_PAGE_OBJECT + 2 * i))
+        .collect();
+    writer.object(
+        PAGES_OBJECT,
+        &format!(
+            "<< /Type /Pages /Kids [{}] /Count {} /MediaBox [0 0 {} {}] >>",
+            kids.join(" "),
+            pages.len(),
+            number(page_size.width),
+            number(page_size.height)
+        ),
+    );
+    writer.object(FONT_OBJECT, &font("Helvetica"));
+    writer.object(BOLD_FONT_OBJECT, &font("Helvetica-Bold"));
+    writer.object(
+        INFO_OBJECT,
+        &format!("<< /Title {} /Producer (Zero OS) >>", string(title)),
+    );
+
+    for (i, page) in pages.iter().enumerate() {
+        let page_object = FIRST_PAGE_OBJECT + 2 * i;
+        writer.object(
+            page_object,
+            &format!(
+                "<< /Type /Page /Parent {} 0 R /Resources << /Font << /F1 {} 0 R /F2 {} 0 R >> >> /Contents {} 0 R >>",
+                PAGES_OBJECT,
+                FONT_OBJECT,
+                BOLD_FONT_OBJECT,
+                page_object + 1
+            ),
+        );
+        let content = content_stream(page, page_size.height);
+        writer.object(
+            page_object + 1,
+            &format!(
+                "<< /Length {} >>\nstream\n{}\nendstream",
+                content.len(),
+                content
+            ),
+        );
+    }
+
+    writer.finish(CATALOG_OBJECT, INFO_OBJECT)
+}
+
+/// A standard Type 1 font in WinAnsi encoding.
+fn font(name: &str) -> String {
+    format!(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+        name
+    )
+}
+
+/// Drawing operators for one page.
+fn content_stream(page: &Page, height: f32) -> String {
+    let mut out = String::new();
+    for item in &page.items {
+        match item {
+            Item::Text {
+                x,
+                y,
+                text,
+                size,
+                bold,
+                color,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "BT /{} {} Tf {} rg {} {} Td {} Tj ET",
+                    if *bold { "F2" } else { "F1" },
+                    number(*size),
+                    rgb(*color),
+                    number(*x),
+                    number(height - y),
+                    string(text)
+                );
+            }
+            Item::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                width,
+                color,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "{} w {} RG {} {} m {} {} l S",
+                    number(*width),
+                    rgb(*color),
+                    number(*x1),
+                    number(height - y1),
+                    number(*x2),
+                    number(height - y2)
+                );
+            }
+            Item::Rect {
+                x,
+                y,
+                width,
+                height: rect_height,
+                fill,
+                stroke,
+                line_width,
+            } => {
+                let paint = match (fill, stroke) {
+                    (Some(_), Some(_)) => "B",
+                    (Some(_), None) => "f",
+                    (None, Some(_)) => "S",
+                    (None, None) => continue,
+                };
+                if let Some(fill) = fill {
+                    let _ = write!(out, "{} rg ", rgb(*fill));
+                }
+                if let Some(stroke) = stroke {
+                    let _ = write!(out, "{} w {} RG ", number(*line_width), rgb(*stroke));
+                }
+                let _ = writeln!(
+                    out,
+                    "{} {} {} {} re {}",
+                    number(*x),
+                    number(height - y - rect_height),
+                    number(*width),
+                    number(*rect_height),
+                    paint
+                );
+            }
+        }
+    }
+    // The stream is followed by a newline before `endstream`
+    out.pop();
+    out
+}
+
+/// A color as PDF operands.
+fn rgb(color: Color) -> String {
+    let [r, g, b] = color;
+    format!(
+        "{} {} {}",
+        number(r as f32 / 255.0),
+        number(g as f32 / 255.0),
+        number(b as f32 / 255.0)
+    )
+}
+
+/// A number with at most two decimals and no trailing zeros.
+fn number(n: f32) -> String {
+    let mut s = format!("{:.2}", n);
+    while s.ends_with('0') {
+        s.pop();
+    }
+    if s.ends_with('.') {
+        s.pop();
+    }
+    if s == "-0" {
+        s = String::from("0");
+    }
+    s
+}
+
+/// A literal string in WinAnsi encoding, escaped.
+fn string(text: &str) -> String {
+    let mut out = String::from("(");
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            // Latin-1 matches WinAnsi from 0xA0; written as octal escapes
+            // so the file stays ASCII
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(out, "\\{:03o}", c as u32);
+            }
+            _ => out.push('?'),
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// Writes objects and tracks their offsets for the cross-reference table.
+#[derive(Default)]
+struct PdfWriter {
+    out: Vec<u8>,
+    /// Byte offset of each object, by object number - 1
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    fn raw(&mut self, s: &str) {
+        self.out.extend_from_slice(s.as_bytes());
+    }
+
+    /// Write object `number`; objects must be written in order.
+    fn object(&mut self, number: usize, body: &str) {
+        debug_assert_eq!(number, self.offsets.len() + 1);
+        self.offsets.push(self.out.len());
+        self.raw(&format!("{} 0 obj\n{}\nendobj\n", number, body));
+    }
+
+    /// Write the cross-reference table and trailer.
+    fn finish(mut self, root: usize, info: usize) -> Vec<u8> {
+        let xref = self.out.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            let _ = writeln!(table, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root {} 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len() + 1,
+            root,
+            info,
+            xref
+        );
+        self.raw(&table);
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::document::BLACK;
+    use super::*;
+    use alloc::vec;
+
+    fn sample() -> Vec<Page> {
+        vec![
+            Page {
+                items: vec![
+                    Item::Text {
+                        x: 72.0,
+                        y: 100.0,
+                        text: String::from("Total (EUR): 5\u{e9}\u{263a}"),
+                        size: 14.0,
+                        bold: true,
+                        color: BLACK,
+                    },
+                    Item::Rect {
+                        x: 10.0,
+                        y: 20.0,
+                        width: 30.0,
+                        height: 40.0,
+                        fill: Some([255, 0, 0]),
+                        stroke: None,
+                        line_width: 1.0,
+                    },
+                ],
+            },
+            Page::default(),
+        ]
+    }
+
+    #[test]
+    fn test_numbers_and_strings() {
+        assert_eq!(number(12.0), "12");
+        assert_eq!(number(0.5), "0.5");
+        assert_eq!(number(1.0 / 3.0), "0.33");
+        assert_eq!(number(-0.001), "0");
+        assert_eq!(string("a(b)\\"), "(a\\(b\\)\\\\)");
+        assert_eq!(string("\u{e9}\u{263a}"), "(\\351?)");
+        assert_eq!(rgb([255, 0, 51]), "1 0 0.2");
+    }
+
+    #[test]
+    fn test_content_flips_y() {
+        let content = content_stream(&sample()[0], 842.0);
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            "BT /F2 14 Tf 0 0 0 rg 72 742 Td (Total \\(EUR\\): 5\\351?) Tj ET"
+        );
+        // Rectangles are anchored at their bottom-left corner
+        assert_eq!(lines[1], "1 0 0 rg 10 782 30 40 re f");
+        assert_eq!(content_stream(&Page::default(), 842.0), "");
+    }
+
+    #[test]
+    fn test_render_structure() {
+        let pdf = render("Invoice", PageSize::A4, &sample());
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Kids [6 0 R 8 0 R] /Count 2 /MediaBox [0 0 595 842]"));
+        assert!(text.contains("/Title (Invoice)"));
+
+        // Every xref entry points at the object it names
+        let xref_at = text.rfind("startxref\n").unwrap();
+        let xref: usize = text[xref_at + 10..]
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let entries: Vec<&str> = text[xref..].lines().skip(3).take(9).collect();
+        for (i, entry) in entries.iter().enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+        assert!(text.contains("/Size 10 /Root 1 0 R /Info 5 0 R"));
+    }
+}
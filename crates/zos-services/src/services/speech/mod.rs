@@ -0,0 +1,407 @@
+//! Speech Service
+//!
+//! The SpeechService is the text output channel for the screen reader. It:
+//! - Accepts text from any process and queues it by priority
+//! - Hands one utterance at a time to the supervisor, which speaks it with
+//!   the browser's `speechSynthesis`
+//! - Lets the user mute individual apps
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - SPEAK: Utterance queued (or the app is muted and it was dropped)
+//! - SET_MUTE: Mute state updated AND the app's pending speech dropped
+//!
+//! **Acceptable partial failure:**
+//! - Supervisor without a speech callback → utterances are reported done
+//!   immediately and nothing is heard
+//!
+//! **Forbidden:**
+//! - Unprivileged processes muting other apps
+//! - Speech from a muted app reaching the supervisor
+//! - Unbounded queue growth (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_SPEAK (0x8500)`: Queue text, optionally interrupting current speech
+//! - `MSG_SPEECH_STOP (0x8502)`: Cancel the caller's speech
+//! - `MSG_SPEECH_SET_MUTE (0x8504)`: Mute or unmute an app
+//! - `MSG_SPEECH_DONE (0x8506)`: Supervisor reports the current utterance ended
+//!
+//! Utterances are sent to the supervisor as `SPEECH:SPEAK:{hex_json}` and
+//! speech is cut off with `SPEECH:CANCEL`, both on the debug channel.
+
+extern crate alloc;
+
+pub mod queue;
+
+use crate::manifests::SPEECH_MANIFEST;
+use crate::response::JsonResponder;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+
+pub use queue::{Priority, RejectReason, SpeechQueue, Utterance, MAX_QUEUED, MAX_TEXT_LEN};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for speech service - re-exported from zos-ipc.
+pub mod speech_msg {
+    pub use zos_ipc::speech::*;
+}
+
+// =============================================================================
+// Permission Constants
+// =============================================================================
+
+/// PIDs allowed to mute apps.
+/// - PID 0: Supervisor
+/// - PID 1: Init
+/// - PID 3: Desktop/Settings UI
+const TRUSTED_PIDS_FOR_MUTE: &[u32] = &[0, 1, 3];
+
+/// PIDs allowed to report utterances done.
+/// The supervisor's messages arrive through Init.
+const TRUSTED_PIDS_FOR_DONE: &[u32] = &[0, 1];
+
+// =============================================================================
+// Request Types
+// =============================================================================
+
+/// Payload of MSG_SPEAK.
+#[derive(Clone, Debug, Deserialize)]
+struct SpeakRequest {
+    text: String,
+    #[serde(default)]
+    priority: Priority,
+    #[serde(default)]
+    interrupt: bool,
+}
+
+/// Payload of MSG_SPEECH_SET_MUTE.
+#[derive(Clone, Debug, Deserialize)]
+struct SetMuteRequest {
+    pid: u32,
+    muted: bool,
+}
+
+// =============================================================================
+// SpeechService Application
+// =============================================================================
+
+/// SpeechService - screen reader text output channel
+#[derive(Default)]
+pub struct SpeechService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// Queued and current utterances
+    queue: SpeechQueue,
+}
+
+impl SpeechService {
+    /// Check if caller may change mute state (Rule 4: fail-closed).
+    fn check_mute_permission(&self, from_pid: u32) -> bool {
+        let allowed = TRUSTED_PIDS_FOR_MUTE.contains(&from_pid);
+        if !allowed {
+            syscall::debug(&format!(
+                "SpeechService: SECURITY - SET_MUTE denied for PID {}",
+                from_pid
+            ));
+        }
+        allowed
+    }
+
+    /// Hand the next utterance to the supervisor if it is idle.
+    fn pump(&mut self) {
+        if let Some(utterance) = self.queue.start_next() {
+            let json = serde_json::to_vec(utterance).unwrap_or_default();
+            let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+            syscall::debug(&format!("{}{}", zos_ipc::debug::SPEECH_SPEAK, hex));
+        }
+    }
+
+    /// Tell the supervisor to stop the utterance being spoken.
+    fn cancel_current(&self) {
+        syscall::debug(zos_ipc::debug::SPEECH_CANCEL);
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_SPEAK
+    fn handle_speak(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = speech_msg::MSG_SPEAK_RESPONSE;
+        let request: SpeakRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid speak request: expected {\"text\": string}",
+                );
+            }
+        };
+
+        let queued = match self.queue.enqueue(
+            msg.from_pid,
+            request.text,
+            request.priority,
+            request.interrupt,
+        ) {
+            Ok(q) => q,
+            Err(RejectReason::Muted) => {
+                let json = br#"{"muted":true}"#;
+                return self.send_response(msg.from_pid, &msg.cap_slots, tag, json);
+            }
+            Err(reason) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    reason.message(),
+                );
+            }
+        };
+
+        if queued.cancel_current {
+            self.cancel_current();
+        }
+        self.pump();
+        let json = format!(r#"{{"id":{}}}"#, queued.id);
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, json.as_bytes())
+    }
+
+    /// Handle MSG_SPEECH_STOP
+    fn handle_stop(&mut self, msg: &Message) -> Result<(), AppError> {
+        let (cancelled, cancel_current) = self.queue.remove_pid(msg.from_pid);
+        if cancel_current {
+            self.cancel_current();
+        }
+        self.pump();
+        let json = format!(r#"{{"cancelled":{}}}"#, cancelled);
+        self.send_response(
+            msg.from_pid,
+            &msg.cap_slots,
+            speech_msg::MSG_SPEECH_STOP_RESPONSE,
+            json.as_bytes(),
+        )
+    }
+
+    /// Handle MSG_SPEECH_SET_MUTE
+    fn handle_set_mute(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = speech_msg::MSG_SPEECH_SET_MUTE_RESPONSE;
+
+        if !self.check_mute_permission(msg.from_pid) {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: SET_MUTE requires system privilege",
+            );
+        }
+        let request: SetMuteRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid mute request: expected {\"pid\": u32, \"muted\": bool}",
+                );
+            }
+        };
+
+        syscall::debug(&format!(
+            "SpeechService: PID {} {} PID {}",
+            msg.from_pid,
+            if request.muted { "mutes" } else { "unmutes" },
+            request.pid
+        ));
+        if self.queue.set_muted(request.pid, request.muted) {
+            self.cancel_current();
+        }
+        self.pump();
+
+        let muted: Vec<u32> = self.queue.muted().collect();
+        let json = serde_json::to_vec(&serde_json::json!({ "muted": muted })).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    /// Handle MSG_SPEECH_DONE
+    fn handle_done(&mut self, msg: &Message) -> Result<(), AppError> {
+        if !TRUSTED_PIDS_FOR_DONE.contains(&msg.from_pid) {
+            syscall::debug(&format!(
+                "SpeechService: SECURITY - DONE from non-system PID {}",
+                msg.from_pid
+            ));
+            return Ok(());
+        }
+        let Some(bytes) = msg.data.get(..4) else {
+            return Ok(());
+        };
+        let id = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+        // Cancelled utterances report done after their replacement started
+        if self.queue.finish(id) {
+            self.pump();
+        }
+        Ok(())
+    }
+}
+
+impl JsonResponder for SpeechService {
+    const SERVICE_NAME: &'static str = "SpeechService";
+}
+
+impl ZeroApp for SpeechService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &SPEECH_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("SpeechService starting (PID {})", ctx.pid));
+
+        // Register with init as "speech" service
+        let service_name = "speech";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("SpeechService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, _ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        match msg.tag {
+            speech_msg::MSG_SPEAK => self.handle_speak(&msg),
+            speech_msg::MSG_SPEECH_STOP => self.handle_stop(&msg),
+            speech_msg::MSG_SPEECH_SET_MUTE => self.handle_set_mute(&msg),
+            speech_msg::MSG_SPEECH_DONE => self.handle_done(&msg),
+            _ => {
+                syscall::debug(&format!(
+                    "SpeechService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("SpeechService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+
+    fn speak(service: &mut SpeechService, pid: u32, json: &str) {
+        let msg = mock_message(speech_msg::MSG_SPEAK, pid, json.as_bytes().to_vec());
+        service.handle_speak(&msg).unwrap();
+    }
+
+    fn done(service: &mut SpeechService, from_pid: u32, id: u32) {
+        let msg = mock_message(
+            speech_msg::MSG_SPEECH_DONE,
+            from_pid,
+            id.to_le_bytes().to_vec(),
+        );
+        service.handle_done(&msg).unwrap();
+    }
+
+    #[test]
+    fn test_speak_request_defaults() {
+        let request: SpeakRequest = serde_json::from_str(r#"{"text":"hi"}"#).unwrap();
+        assert_eq!(request.priority, Priority::Normal);
+        assert!(!request.interrupt);
+
+        let request: SpeakRequest =
+            serde_json::from_str(r#"{"text":"hi","priority":"high","interrupt":true}"#).unwrap();
+        assert_eq!(request.priority, Priority::High);
+        assert!(request.interrupt);
+    }
+
+    #[test]
+    fn test_speak_starts_first_and_done_advances() {
+        let mut service = SpeechService::default();
+        speak(&mut service, 10, r#"{"text":"one"}"#);
+        speak(&mut service, 10, r#"{"text":"two"}"#);
+
+        let first = service.queue.current().unwrap().id;
+        assert_eq!(service.queue.len(), 1);
+
+        // Only the supervisor (via Init) can report done
+        done(&mut service, 10, first);
+        assert_eq!(service.queue.current().unwrap().id, first);
+
+        done(&mut service, 1, first);
+        assert_eq!(service.queue.current().unwrap().text, "two");
+    }
+
+    #[test]
+    fn test_mute_requires_trusted_pid() {
+        let service = SpeechService::default();
+        for &pid in TRUSTED_PIDS_FOR_MUTE {
+            assert!(service.check_mute_permission(pid));
+        }
+        assert!(!service.check_mute_permission(100));
+
+        let mut service = SpeechService::default();
+        let msg = mock_message(
+            speech_msg::MSG_SPEECH_SET_MUTE,
+            100,
+            br#"{"pid":10,"muted":true}"#.to_vec(),
+        );
+        service.handle_set_mute(&msg).unwrap();
+        assert_eq!(service.queue.muted().count(), 0);
+
+        let msg = mock_message(
+            speech_msg::MSG_SPEECH_SET_MUTE,
+            3,
+            br#"{"pid":10,"muted":true}"#.to_vec(),
+        );
+        service.handle_set_mute(&msg).unwrap();
+        speak(&mut service, 10, r#"{"text":"ignored"}"#);
+        assert!(service.queue.current().is_none());
+    }
+
+    #[test]
+    fn test_stop_cancels_only_caller() {
+        let mut service = SpeechService::default();
+        speak(&mut service, 10, r#"{"text":"mine"}"#);
+        speak(&mut service, 11, r#"{"text":"theirs"}"#);
+
+        let msg = mock_message(speech_msg::MSG_SPEECH_STOP, 10, Vec::new());
+        service.handle_stop(&msg).unwrap();
+        assert_eq!(service.queue.current().unwrap().text, "theirs");
+    }
+}
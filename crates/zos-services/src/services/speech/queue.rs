@@ -0,0 +1,323 @@
+//! Utterance queue
+//!
+//! Utterances are spoken one at a time, highest priority first and FIFO
+//! within a priority. At most one utterance is *current* (handed to the
+//! supervisor and not yet reported done); the rest wait in the queue.
+//!
+//! An interrupting utterance flushes the queue and cancels the current one,
+//! which is how focus announcements cut off stale speech.
+
+use alloc::collections::{BTreeSet, VecDeque};
+use alloc::string::String;
+use serde::{Deserialize, Serialize};
+
+/// Maximum queued utterances (DoS protection per Rule 11).
+pub const MAX_QUEUED: usize = 32;
+
+/// Maximum utterance length in bytes.
+pub const MAX_TEXT_LEN: usize = 4096;
+
+/// Utterance priority.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// A queued or current utterance.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Utterance {
+    pub id: u32,
+    /// Requesting process
+    pub pid: u32,
+    pub text: String,
+    pub priority: Priority,
+}
+
+/// Why an utterance was not queued.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The requesting app is muted
+    Muted,
+    /// Empty text
+    Empty,
+    /// Text longer than [`MAX_TEXT_LEN`]
+    TooLong,
+    /// Queue full of utterances at the same or higher priority
+    QueueFull,
+}
+
+impl RejectReason {
+    /// Message for error responses.
+    pub fn message(self) -> &'static str {
+        match self {
+            RejectReason::Muted => "App is muted",
+            RejectReason::Empty => "Nothing to speak",
+            RejectReason::TooLong => "Text too long",
+            RejectReason::QueueFull => "Speech queue full",
+        }
+    }
+}
+
+/// Result of queueing an utterance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Queued {
+    pub id: u32,
+    /// The current utterance must be cancelled before the next is spoken
+    pub cancel_current: bool,
+}
+
+/// Speech queue with per-app mute.
+#[derive(Debug, Default)]
+pub struct SpeechQueue {
+    queue: VecDeque<Utterance>,
+    current: Option<Utterance>,
+    muted: BTreeSet<u32>,
+    next_id: u32,
+}
+
+impl SpeechQueue {
+    /// Queue an utterance.
+    pub fn enqueue(
+        &mut self,
+        pid: u32,
+        text: String,
+        priority: Priority,
+        interrupt: bool,
+    ) -> Result<Queued, RejectReason> {
+        if self.muted.contains(&pid) {
+            return Err(RejectReason::Muted);
+        }
+        if text.trim().is_empty() {
+            return Err(RejectReason::Empty);
+        }
+        if text.len() > MAX_TEXT_LEN {
+            return Err(RejectReason::TooLong);
+        }
+
+        let mut cancel_current = false;
+        if interrupt {
+            self.queue.clear();
+            cancel_current = self.current.take().is_some();
+        } else if self.queue.len() >= MAX_QUEUED {
+            // Make room by dropping the newest lowest-priority utterance
+            let lowest = self.queue.iter().map(|u| u.priority).min();
+            match lowest {
+                Some(lowest) if lowest < priority => {
+                    if let Some(index) = self.queue.iter().rposition(|u| u.priority == lowest) {
+                        self.queue.remove(index);
+                    }
+                }
+                _ => return Err(RejectReason::QueueFull),
+            }
+        }
+
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        let utterance = Utterance {
+            id: self.next_id,
+            pid,
+            text,
+            priority,
+        };
+        let index = self
+            .queue
+            .iter()
+            .position(|u| u.priority < priority)
+            .unwrap_or(self.queue.len());
+        self.queue.insert(index, utterance);
+
+        Ok(Queued {
+            id: self.next_id,
+            cancel_current,
+        })
+    }
+
+    /// Start the next utterance if nothing is being spoken.
+    pub fn start_next(&mut self) -> Option<&Utterance> {
+        if self.current.is_none() {
+            self.current = self.queue.pop_front();
+            return self.current.as_ref();
+        }
+        None
+    }
+
+    /// Mark the current utterance as done.
+    ///
+    /// Returns false for a stale or unknown ID.
+    pub fn finish(&mut self, id: u32) -> bool {
+        if self.current.as_ref().is_some_and(|u| u.id == id) {
+            self.current = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Drop a process's utterances.
+    ///
+    /// Returns how many were dropped and whether the current one was among
+    /// them (and so must be cancelled).
+    pub fn remove_pid(&mut self, pid: u32) -> (u32, bool) {
+        let before = self.queue.len();
+        self.queue.retain(|u| u.pid != pid);
+        let mut removed = (before - self.queue.len()) as u32;
+
+        let cancel_current = self.current.as_ref().is_some_and(|u| u.pid == pid);
+        if cancel_current {
+            self.current = None;
+            removed += 1;
+        }
+        (removed, cancel_current)
+    }
+
+    /// Mute or unmute a process.
+    ///
+    /// Muting also drops its pending utterances; returns whether the
+    /// current utterance must be cancelled.
+    pub fn set_muted(&mut self, pid: u32, muted: bool) -> bool {
+        if muted {
+            self.muted.insert(pid);
+            self.remove_pid(pid).1
+        } else {
+            self.muted.remove(&pid);
+            false
+        }
+    }
+
+    /// Muted PIDs, ascending.
+    pub fn muted(&self) -> impl Iterator<Item = u32> + '_ {
+        self.muted.iter().copied()
+    }
+
+    /// Utterance being spoken.
+    pub fn current(&self) -> Option<&Utterance> {
+        self.current.as_ref()
+    }
+
+    /// Number of utterances waiting.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    fn speak(queue: &mut SpeechQueue, pid: u32, text: &str, priority: Priority) -> u32 {
+        queue
+            .enqueue(pid, text.to_string(), priority, false)
+            .unwrap()
+            .id
+    }
+
+    fn drain(queue: &mut SpeechQueue) -> Vec<String> {
+        let mut spoken = Vec::new();
+        while let Some(u) = queue.start_next() {
+            let (id, text) = (u.id, u.text.clone());
+            spoken.push(text);
+            queue.finish(id);
+        }
+        spoken
+    }
+
+    #[test]
+    fn test_priority_then_fifo() {
+        let mut queue = SpeechQueue::default();
+        speak(&mut queue, 10, "a", Priority::Normal);
+        speak(&mut queue, 10, "b", Priority::Low);
+        speak(&mut queue, 10, "c", Priority::High);
+        speak(&mut queue, 10, "d", Priority::Normal);
+        assert_eq!(drain(&mut queue), ["c", "a", "d", "b"]);
+    }
+
+    #[test]
+    fn test_one_utterance_at_a_time() {
+        let mut queue = SpeechQueue::default();
+        let first = speak(&mut queue, 10, "one", Priority::Normal);
+        speak(&mut queue, 10, "two", Priority::Normal);
+
+        assert_eq!(queue.start_next().map(|u| u.id), Some(first));
+        assert!(queue.start_next().is_none());
+        assert!(!queue.finish(first + 1));
+        assert!(queue.finish(first));
+        assert_eq!(queue.start_next().unwrap().text, "two");
+    }
+
+    #[test]
+    fn test_interrupt_flushes_and_cancels() {
+        let mut queue = SpeechQueue::default();
+        speak(&mut queue, 10, "old", Priority::Normal);
+        speak(&mut queue, 10, "older", Priority::High);
+        queue.start_next();
+
+        let queued = queue
+            .enqueue(1, "Terminal window".to_string(), Priority::High, true)
+            .unwrap();
+        assert!(queued.cancel_current);
+        assert_eq!(drain(&mut queue), ["Terminal window"]);
+
+        // Nothing to cancel when idle
+        let queued = queue
+            .enqueue(1, "again".to_string(), Priority::High, true)
+            .unwrap();
+        assert!(!queued.cancel_current);
+    }
+
+    #[test]
+    fn test_mute_drops_and_rejects() {
+        let mut queue = SpeechQueue::default();
+        speak(&mut queue, 10, "noisy", Priority::Normal);
+        speak(&mut queue, 11, "quiet", Priority::Normal);
+        queue.start_next();
+
+        assert!(queue.set_muted(10, true));
+        assert_eq!(
+            queue.enqueue(10, "more".to_string(), Priority::High, false),
+            Err(RejectReason::Muted)
+        );
+        assert_eq!(drain(&mut queue), ["quiet"]);
+        assert_eq!(queue.muted().collect::<Vec<_>>(), [10]);
+
+        queue.set_muted(10, false);
+        speak(&mut queue, 10, "back", Priority::Normal);
+    }
+
+    #[test]
+    fn test_full_queue_evicts_lower_priority() {
+        let mut queue = SpeechQueue::default();
+        for _ in 0..MAX_QUEUED {
+            speak(&mut queue, 10, "chatter", Priority::Low);
+        }
+        assert_eq!(
+            queue.enqueue(10, "x".to_string(), Priority::Low, false),
+            Err(RejectReason::QueueFull)
+        );
+        speak(&mut queue, 10, "important", Priority::High);
+        assert_eq!(queue.len(), MAX_QUEUED);
+        assert_eq!(queue.start_next().unwrap().text, "important");
+    }
+
+    #[test]
+    fn test_rejects_bad_text() {
+        let mut queue = SpeechQueue::default();
+        assert_eq!(
+            queue.enqueue(10, "  ".to_string(), Priority::Normal, false),
+            Err(RejectReason::Empty)
+        );
+        let long = "a".repeat(MAX_TEXT_LEN + 1);
+        assert_eq!(
+            queue.enqueue(10, long, Priority::Normal, false),
+            Err(RejectReason::TooLong)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Thumbnail cache
+//!
+//! Thumbnails live in the VFS under [`CACHE_DIR`], one file per source
+//! image and size, named after the SHA-256 of the source's content. Two
+//! copies of an image share a thumbnail, and an edited image gets a new
+//! one. The metadata for all of them is a [`ThumbnailIndex`] persisted at
+//! [`INDEX_PATH`]; when the cache outgrows its limits, the least recently
+//! used thumbnails are evicted.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// VFS directory holding thumbnails.
+pub const CACHE_DIR: &str = "/var/cache/thumbnails";
+
+/// VFS path of the persisted [`ThumbnailIndex`].
+pub const INDEX_PATH: &str = "/var/cache/thumbnails/index.json";
+
+/// Maximum number of cached thumbnails.
+pub const MAX_CACHE_ENTRIES: usize = 2048;
+
+/// Maximum total size of cached thumbnails, in bytes.
+pub const MAX_CACHE_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Index key of the thumbnail of content `hash` at edge length `size`.
+pub fn cache_key(hash: &str, size: u32) -> String {
+    format!("{}-{}", hash, size)
+}
+
+/// Metadata of one cached thumbnail.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// SHA-256 of the source image (hex)
+    pub hash: String,
+    /// Edge length asked for
+    pub size: u32,
+    /// Actual dimensions, at most `size` on each edge
+    pub width: u16,
+    pub height: u16,
+    /// MIME type of the thumbnail file
+    pub mime: String,
+    /// File extension, without the dot
+    pub ext: String,
+    /// Size of the thumbnail file, in bytes
+    pub bytes: u64,
+    /// Wall-clock time of last use (ms)
+    pub last_used_ms: u64,
+}
+
+impl CacheEntry {
+    /// VFS path of the thumbnail file.
+    pub fn path(&self) -> String {
+        format!(
+            "{}/{}.{}",
+            CACHE_DIR,
+            cache_key(&self.hash, self.size),
+            self.ext
+        )
+    }
+}
+
+/// Metadata of every cached thumbnail, by [`cache_key`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThumbnailIndex {
+    #[serde(default)]
+    pub entries: BTreeMap<String, CacheEntry>,
+}
+
+impl ThumbnailIndex {
+    /// Find the thumbnail under `key`, marking it used at `now_ms`.
+    pub fn get(&mut self, key: &str, now_ms: u64) -> Option<&CacheEntry> {
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used_ms = now_ms;
+        Some(entry)
+    }
+
+    /// Total size of cached thumbnails.
+    pub fn total_bytes(&self) -> u64 {
+        self.entries.values().map(|e| e.bytes).sum()
+    }
+
+    /// Add or replace the thumbnail under `key`, evicting the least
+    /// recently used thumbnails to stay within [`MAX_CACHE_ENTRIES`] and
+    /// [`MAX_CACHE_BYTES`].
+    ///
+    /// Returns the evicted entries, whose files must be deleted.
+    pub fn insert(&mut self, key: String, entry: CacheEntry) -> Vec<CacheEntry> {
+        self.entries.insert(key.clone(), entry);
+
+        let mut evicted = Vec::new();
+        let mut total = self.total_bytes();
+        while self.entries.len() > MAX_CACHE_ENTRIES || total > MAX_CACHE_BYTES {
+            let Some(victim) = self
+                .entries
+                .iter()
+                .filter(|(k, _)| **k != key)
+                .min_by_key(|(_, e)| e.last_used_ms)
+                .map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            if let Some(e) = self.entries.remove(&victim) {
+                total -= e.bytes;
+                evicted.push(e);
+            }
+        }
+        evicted
+    }
+
+    /// Add every entry of `other` to this index, as [`insert`](Self::insert)
+    /// does. Returns the evicted entries.
+    pub fn absorb(&mut self, other: ThumbnailIndex) -> Vec<CacheEntry> {
+        let mut evicted = Vec::new();
+        for (key, entry) in other.entries {
+            evicted.extend(self.insert(key, entry));
+        }
+        evicted
+    }
+
+    /// Serialize to JSON bytes.
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes.
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: &str, bytes: u64, last_used_ms: u64) -> CacheEntry {
+        CacheEntry {
+            hash: String::from(hash),
+            size: 128,
+            width: 128,
+            height: 96,
+            mime: String::from("image/webp"),
+            ext: String::from("webp"),
+            bytes,
+            last_used_ms,
+        }
+    }
+
+    #[test]
+    fn test_get_touches_and_paths() {
+        let mut index = ThumbnailIndex::default();
+        index.insert(cache_key("ab", 128), entry("ab", 10, 5));
+        assert!(index.get("ab-64", 9).is_none());
+
+        let found = index.get("ab-128", 9).unwrap();
+        assert_eq!(found.last_used_ms, 9);
+        assert_eq!(found.path(), "/var/cache/thumbnails/ab-128.webp");
+
+        let restored = ThumbnailIndex::from_json(&index.to_json()).unwrap();
+        assert_eq!(restored, index);
+        assert!(ThumbnailIndex::from_json(b"not json").is_none());
+    }
+
+    #[test]
+    fn test_insert_evicts_least_recently_used() {
+        let mut index = ThumbnailIndex::default();
+        let half = MAX_CACHE_BYTES / 2;
+        assert!(index
+            .insert(String::from("a"), entry("a", half, 1))
+            .is_empty());
+        assert!(index
+            .insert(String::from("b"), entry("b", half, 2))
+            .is_empty());
+        index.get("a", 3);
+
+        // "b" is now the least recently used
+        let evicted = index.insert(String::from("c"), entry("c", 1, 4));
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].hash, "b");
+        assert_eq!(index.total_bytes(), half + 1);
+
+        // A thumbnail bigger than the cache still replaces everything else
+        let evicted = index.insert(String::from("d"), entry("d", MAX_CACHE_BYTES + 1, 5));
+        assert_eq!(evicted.len(), 2);
+        assert_eq!(index.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_absorb() {
+        let mut index = ThumbnailIndex::default();
+        index.insert(String::from("a"), entry("a", 1, 1));
+        let mut loaded = ThumbnailIndex::default();
+        loaded.insert(String::from("b"), entry("b", 1, 2));
+        assert!(loaded.absorb(index).is_empty());
+        assert_eq!(loaded.entries.len(), 2);
+    }
+}
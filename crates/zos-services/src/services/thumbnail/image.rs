@@ -0,0 +1,183 @@
+//! Image formats and decode results
+//!
+//! The service never decodes pixels itself: the browser already has
+//! decoders for every format worth supporting, so the supervisor decodes
+//! and scales with `createImageBitmap` and sends back an encoded thumbnail.
+//! This module only recognises formats by their signature bytes, so files
+//! the browser can't decode are refused before they are sent, and parses
+//! what the supervisor sends back.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Thumbnail edge lengths, in pixels. Requests are rounded up to one of
+/// these so that a few sizes serve every caller.
+pub const SIZES: [u32; 3] = [64, 128, 256];
+
+/// Edge length when a request gives none.
+pub const DEFAULT_SIZE: u32 = 128;
+
+/// An image format, as recognised by its signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+    Bmp,
+}
+
+impl ImageFormat {
+    /// Recognise the format of an image file from its first bytes.
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(ImageFormat::Jpeg)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            Some(ImageFormat::Webp)
+        } else if bytes.starts_with(b"BM") && bytes.len() >= 26 {
+            Some(ImageFormat::Bmp)
+        } else {
+            None
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::Webp => "image/webp",
+            ImageFormat::Bmp => "image/bmp",
+        }
+    }
+
+    /// File extension, without the dot.
+    pub fn ext(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Gif => "gif",
+            ImageFormat::Webp => "webp",
+            ImageFormat::Bmp => "bmp",
+        }
+    }
+}
+
+/// Round a requested edge length up to the nearest of [`SIZES`].
+pub fn snap_size(requested: Option<u32>) -> u32 {
+    let requested = requested.unwrap_or(DEFAULT_SIZE);
+    SIZES
+        .iter()
+        .copied()
+        .find(|&size| size >= requested)
+        .unwrap_or(SIZES[SIZES.len() - 1])
+}
+
+/// What the supervisor made of one decode (MSG_THUMBNAIL_DECODED).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decoded {
+    pub id: u32,
+    pub result: Result<Thumbnail, String>,
+}
+
+/// An encoded thumbnail and its dimensions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Thumbnail {
+    pub width: u16,
+    pub height: u16,
+    pub bytes: Vec<u8>,
+}
+
+impl Decoded {
+    /// Parse `[id: u32, ok: u8, ...]`; see
+    /// [`zos_ipc::thumbnail::MSG_THUMBNAIL_DECODED`].
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 5 {
+            return None;
+        }
+        let id = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+        let result = if data[4] == 0 {
+            Err(String::from_utf8_lossy(&data[5..]).into_owned())
+        } else {
+            if data.len() < 9 {
+                return None;
+            }
+            Ok(Thumbnail {
+                width: u16::from_le_bytes([data[5], data[6]]),
+                height: u16::from_le_bytes([data[7], data[8]]),
+                bytes: data[9..].to_vec(),
+            })
+        };
+        Some(Decoded { id, result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(
+            ImageFormat::sniff(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(
+            ImageFormat::sniff(&[0xff, 0xd8, 0xff, 0xe0]),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(ImageFormat::sniff(b"GIF89a\x01\0"), Some(ImageFormat::Gif));
+        assert_eq!(
+            ImageFormat::sniff(b"RIFF\x24\0\0\0WEBPVP8 "),
+            Some(ImageFormat::Webp)
+        );
+        assert_eq!(ImageFormat::sniff(b"RIFF\x24\0\0\0WAVEfmt "), None);
+        assert_eq!(ImageFormat::sniff(b"BM"), None);
+        assert_eq!(ImageFormat::sniff(b"%PDF-1.4"), None);
+        assert_eq!(ImageFormat::sniff(b""), None);
+    }
+
+    #[test]
+    fn test_snap_size() {
+        assert_eq!(snap_size(None), 128);
+        assert_eq!(snap_size(Some(1)), 64);
+        assert_eq!(snap_size(Some(64)), 64);
+        assert_eq!(snap_size(Some(65)), 128);
+        assert_eq!(snap_size(Some(4096)), 256);
+    }
+
+    #[test]
+    fn test_parse_decoded() {
+        let mut ok = 7u32.to_le_bytes().to_vec();
+        ok.push(1);
+        ok.extend_from_slice(&128u16.to_le_bytes());
+        ok.extend_from_slice(&96u16.to_le_bytes());
+        ok.extend_from_slice(b"RIFF");
+        assert_eq!(
+            Decoded::parse(&ok),
+            Some(Decoded {
+                id: 7,
+                result: Ok(Thumbnail {
+                    width: 128,
+                    height: 96,
+                    bytes: b"RIFF".to_vec(),
+                }),
+            })
+        );
+
+        let mut failed = 8u32.to_le_bytes().to_vec();
+        failed.push(0);
+        failed.extend_from_slice(b"Not an image");
+        assert_eq!(
+            Decoded::parse(&failed).unwrap().result,
+            Err(String::from("Not an image"))
+        );
+
+        assert!(Decoded::parse(&[1, 0, 0]).is_none());
+        assert!(Decoded::parse(&[1, 0, 0, 0, 1, 5]).is_none());
+    }
+}
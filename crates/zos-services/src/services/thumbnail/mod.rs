@@ -0,0 +1,764 @@
+//! Thumbnail Service
+//!
+//! The ThumbnailService makes small previews of image files for the file
+//! manager and image viewer. A request names an image in the VFS; the
+//! service reads it, hashes its content and answers with the path of a
+//! cached thumbnail (see [`cache`]), making one first if needed.
+//!
+//! Thumbnails are made by the supervisor: the source image goes out on
+//! the debug channel, the browser decodes and scales it, and the encoded
+//! thumbnail comes back as MSG_THUMBNAIL_DECODED (see [`image`]). Requests
+//! for an image that is already being decoded wait for that decode.
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - GET: Source read AND thumbnail written under `/var/cache/thumbnails`
+//!   AND recorded in the index (or found there)
+//!
+//! **Acceptable partial failure:**
+//! - Index save or eviction cleanup fails → logged; the next save rewrites
+//!   the index, and orphaned files are only wasted space
+//! - Supervisor without a thumbnail callback → requests fail with an error
+//!
+//! **Forbidden:**
+//! - Answering with a thumbnail of different content than the source has
+//! - Accepting decode results from anyone but the supervisor
+//! - Unbounded request, decode or cache growth (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_THUMBNAIL_GET (0x8B00)`: Get the thumbnail of an image
+//! - `MSG_THUMBNAIL_DECODED (0x8B02)`: Supervisor reports a decode finished
+//!
+//! Decodes are sent to the supervisor as
+//! `THUMBNAIL:DECODE:{id}:{size}:{hex_data}` on the debug channel.
+//!
+//! # Storage Access
+//!
+//! This service uses VFS IPC (async/await pattern, see
+//! [`zos_vfs::client::futures`]) to read images and keep its cache. All
+//! storage operations flow through the VFS service per Invariant 31.
+
+extern crate alloc;
+
+pub mod cache;
+pub mod image;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+use zos_vfs::client::futures::{Vfs, VfsExecutor};
+
+use crate::manifests::THUMBNAIL_MANIFEST;
+use crate::response::JsonResponder;
+use crate::services::identity::utils::bytes_to_hex;
+
+pub use cache::{cache_key, CacheEntry, ThumbnailIndex, CACHE_DIR, INDEX_PATH};
+pub use image::{snap_size, Decoded, ImageFormat, Thumbnail};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for thumbnail service - re-exported from zos-ipc.
+pub mod thumbnail_msg {
+    pub use zos_ipc::thumbnail::*;
+}
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Maximum requests being answered, including those waiting on a decode
+/// (DoS protection per Rule 11).
+const MAX_REQUESTS: usize = 16;
+
+/// Largest image that will be read, in bytes. The whole image is held
+/// while it is hashed and sent to the supervisor.
+const MAX_SOURCE_SIZE: usize = 8 * 1024 * 1024;
+
+/// PIDs allowed to report decodes.
+/// The supervisor's messages arrive through Init.
+const TRUSTED_PIDS_FOR_DECODED: &[u32] = &[0, 1];
+
+// =============================================================================
+// Request Types
+// =============================================================================
+
+/// Payload of MSG_THUMBNAIL_GET.
+#[derive(Clone, Debug, Deserialize)]
+struct GetRequest {
+    path: String,
+    #[serde(default)]
+    size: Option<u32>,
+}
+
+/// A GET being answered.
+#[derive(Clone, Debug)]
+struct Pending {
+    pid: u32,
+    cap_slots: Vec<u32>,
+    /// Normalized source path
+    source: String,
+    /// Edge length, snapped to [`image::SIZES`]
+    size: u32,
+}
+
+/// A decode sent to the supervisor, or a thumbnail being written.
+#[derive(Clone, Debug)]
+struct Decode {
+    key: String,
+    hash: String,
+    size: u32,
+    /// Requests waiting for the thumbnail
+    requests: Vec<u32>,
+    /// The thumbnail's index entry, once it is being written
+    entry: Option<CacheEntry>,
+}
+
+// =============================================================================
+// VFS Tasks
+// =============================================================================
+
+/// A finished VFS task.
+enum Done {
+    /// Cache directory made and the persisted index read, if there was one
+    Loaded(Option<ThumbnailIndex>),
+    /// Source image of a request read
+    Read {
+        request: u32,
+        result: Result<Vec<u8>, String>,
+    },
+    /// Thumbnail of a decode written
+    Stored {
+        decode: u32,
+        result: Result<(), String>,
+    },
+    /// Index saved and evicted thumbnails deleted
+    Saved(Result<(), String>),
+}
+
+/// Make the cache directory and read the persisted index.
+async fn load_index(vfs: Vfs) -> Done {
+    if !vfs.exists(CACHE_DIR).await.unwrap_or(false) {
+        if let Err(e) = vfs.mkdir(CACHE_DIR).await {
+            syscall::debug(&format!(
+                "ThumbnailService: Failed to create {}: {}",
+                CACHE_DIR, e
+            ));
+        }
+        return Done::Loaded(None);
+    }
+    let index = match vfs.read(INDEX_PATH).await {
+        Ok(data) => ThumbnailIndex::from_json(&data),
+        Err(_) => None,
+    };
+    Done::Loaded(index)
+}
+
+/// Save the index and delete evicted thumbnails.
+async fn save_index(vfs: Vfs, index: Vec<u8>, evicted: Vec<String>) -> Done {
+    for path in evicted {
+        if let Err(e) = vfs.unlink(&path).await {
+            syscall::debug(&format!(
+                "ThumbnailService: Failed to delete {}: {}",
+                path, e
+            ));
+        }
+    }
+    Done::Saved(vfs.write(INDEX_PATH, &index).await)
+}
+
+/// SHA-256 of `data`, in hex.
+fn content_hash(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    bytes_to_hex(&hasher.finalize())
+}
+
+// =============================================================================
+// ThumbnailService Application
+// =============================================================================
+
+/// ThumbnailService - cached image thumbnails
+pub struct ThumbnailService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// Cached thumbnails
+    index: ThumbnailIndex,
+    /// Requests being answered, by request ID
+    requests: BTreeMap<u32, Pending>,
+    next_request: u32,
+    /// Decodes in progress, by decode ID
+    decodes: BTreeMap<u32, Decode>,
+    next_decode: u32,
+    /// VFS work in progress
+    tasks: VfsExecutor<Done>,
+    /// Wall-clock time of the last message (ms)
+    now_ms: u64,
+}
+
+impl Default for ThumbnailService {
+    fn default() -> Self {
+        Self {
+            registered: false,
+            index: ThumbnailIndex::default(),
+            requests: BTreeMap::new(),
+            next_request: 1,
+            decodes: BTreeMap::new(),
+            next_decode: 1,
+            tasks: VfsExecutor::new(),
+            now_ms: 0,
+        }
+    }
+}
+
+impl ThumbnailService {
+    /// Handle every finished VFS task.
+    fn finish_tasks(&mut self) {
+        while let Some(done) = self.tasks.next_finished() {
+            match done {
+                Done::Loaded(loaded) => self.on_loaded(loaded),
+                Done::Read { request, result } => self.on_read(request, result),
+                Done::Stored { decode, result } => self.on_stored(decode, result),
+                Done::Saved(Err(e)) => {
+                    syscall::debug(&format!("ThumbnailService: Index save failed: {}", e));
+                }
+                Done::Saved(Ok(())) => {}
+            }
+        }
+    }
+
+    /// Take in the persisted index. Thumbnails made before it was read are
+    /// kept.
+    fn on_loaded(&mut self, loaded: Option<ThumbnailIndex>) {
+        let Some(mut loaded) = loaded else {
+            return;
+        };
+        let made = core::mem::take(&mut self.index);
+        let changed = !made.entries.is_empty();
+        let evicted = loaded.absorb(made);
+        self.index = loaded;
+        syscall::debug(&format!(
+            "ThumbnailService: Loaded index ({} thumbnails)",
+            self.index.entries.len()
+        ));
+        if changed || !evicted.is_empty() {
+            self.save(evicted);
+        }
+    }
+
+    /// Handle the source image of a request.
+    fn on_read(&mut self, request: u32, result: Result<Vec<u8>, String>) {
+        let Some(pending) = self.requests.get(&request) else {
+            return;
+        };
+        let data = match result {
+            Ok(data) => data,
+            Err(e) => {
+                return self.fail(
+                    request,
+                    &format!("Failed to read {}: {}", pending.source, e),
+                )
+            }
+        };
+        if data.len() > MAX_SOURCE_SIZE {
+            return self.fail(request, &format!("Image exceeds {} bytes", MAX_SOURCE_SIZE));
+        }
+        if ImageFormat::sniff(&data).is_none() {
+            return self.fail(request, "Not a supported image format");
+        }
+
+        let size = pending.size;
+        let hash = content_hash(&data);
+        let key = cache_key(&hash, size);
+        if let Some(entry) = self.index.get(&key, self.now_ms).cloned() {
+            return self.answer(request, &entry, true);
+        }
+        if let Some(decode) = self.decodes.values_mut().find(|d| d.key == key) {
+            decode.requests.push(request);
+            return;
+        }
+
+        let id = self.next_decode;
+        self.next_decode = self.next_decode.wrapping_add(1).max(1);
+        self.decodes.insert(
+            id,
+            Decode {
+                key,
+                hash,
+                size,
+                requests: alloc::vec![request],
+                entry: None,
+            },
+        );
+        let hex = bytes_to_hex(&data);
+        syscall::debug(&format!(
+            "{}{}:{}:{}",
+            zos_ipc::debug::THUMBNAIL_DECODE,
+            id,
+            size,
+            hex
+        ));
+    }
+
+    /// Write a decoded thumbnail to the cache.
+    fn on_decoded(&mut self, decoded: Decoded) {
+        let Some(decode) = self.decodes.get_mut(&decoded.id) else {
+            return;
+        };
+        if decode.entry.is_some() {
+            // Reported twice; the first is being written
+            return;
+        }
+        let thumbnail = match decoded.result {
+            Ok(thumbnail) => thumbnail,
+            Err(e) => return self.fail_decode(decoded.id, &format!("Decode failed: {}", e)),
+        };
+        let Some(format) = ImageFormat::sniff(&thumbnail.bytes) else {
+            return self.fail_decode(decoded.id, "Decoder returned an unknown format");
+        };
+        let entry = CacheEntry {
+            hash: decode.hash.clone(),
+            size: decode.size,
+            width: thumbnail.width,
+            height: thumbnail.height,
+            mime: String::from(format.mime()),
+            ext: String::from(format.ext()),
+            bytes: thumbnail.bytes.len() as u64,
+            last_used_ms: self.now_ms,
+        };
+        let path = entry.path();
+        decode.entry = Some(entry);
+
+        let vfs = self.tasks.handle();
+        let id = decoded.id;
+        self.tasks.spawn(async move {
+            Done::Stored {
+                decode: id,
+                result: vfs.write(&path, &thumbnail.bytes).await,
+            }
+        });
+        self.finish_tasks();
+    }
+
+    /// Record a written thumbnail and answer the requests waiting for it.
+    fn on_stored(&mut self, id: u32, result: Result<(), String>) {
+        if let Err(e) = result {
+            return self.fail_decode(id, &format!("Failed to cache thumbnail: {}", e));
+        }
+        let Some(decode) = self.decodes.remove(&id) else {
+            return;
+        };
+        let Some(entry) = decode.entry else {
+            return;
+        };
+        let evicted = self.index.insert(decode.key, entry.clone());
+        self.save(evicted);
+        for request in decode.requests {
+            self.answer(request, &entry, false);
+        }
+    }
+
+    /// Persist the index, deleting the files of `evicted` thumbnails.
+    fn save(&mut self, evicted: Vec<CacheEntry>) {
+        let vfs = self.tasks.handle();
+        let index = self.index.to_json();
+        let paths = evicted.iter().map(CacheEntry::path).collect();
+        self.tasks.spawn(save_index(vfs, index, paths));
+    }
+
+    /// Answer a request with a cached thumbnail.
+    fn answer(&mut self, request: u32, entry: &CacheEntry, cached: bool) {
+        let Some(pending) = self.requests.remove(&request) else {
+            return;
+        };
+        let json = serde_json::to_vec(&serde_json::json!({
+            "source": pending.source,
+            "path": entry.path(),
+            "hash": entry.hash,
+            "size": entry.size,
+            "width": entry.width,
+            "height": entry.height,
+            "mime": entry.mime,
+            "cached": cached,
+        }))
+        .unwrap_or_default();
+        let _ = self.send_response(
+            pending.pid,
+            &pending.cap_slots,
+            thumbnail_msg::MSG_THUMBNAIL_GET_RESPONSE,
+            &json,
+        );
+    }
+
+    /// Answer a request with an error.
+    fn fail(&mut self, request: u32, error: &str) {
+        let Some(pending) = self.requests.remove(&request) else {
+            return;
+        };
+        syscall::debug(&format!("ThumbnailService: {} ({})", error, pending.source));
+        let _ = self.send_error_response(
+            pending.pid,
+            &pending.cap_slots,
+            thumbnail_msg::MSG_THUMBNAIL_GET_RESPONSE,
+            error,
+        );
+    }
+
+    /// Drop a decode and fail the requests waiting for it.
+    fn fail_decode(&mut self, id: u32, error: &str) {
+        if let Some(decode) = self.decodes.remove(&id) {
+            for request in decode.requests {
+                self.fail(request, error);
+            }
+        }
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_THUMBNAIL_GET
+    fn handle_get(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = thumbnail_msg::MSG_THUMBNAIL_GET_RESPONSE;
+        let request: GetRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid thumbnail request: expected {\"path\": string, \"size\": u32?}",
+                );
+            }
+        };
+        let source = match zos_vfs::normalize_path(&request.path) {
+            Ok(path) => path,
+            Err(e) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    &format!("Invalid path: {:?}", e),
+                );
+            }
+        };
+        if self.requests.len() >= MAX_REQUESTS {
+            syscall::debug(&format!(
+                "ThumbnailService: Request limit reached ({}/{})",
+                self.requests.len(),
+                MAX_REQUESTS
+            ));
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Too many thumbnail requests, try again later",
+            );
+        }
+
+        let id = self.next_request;
+        self.next_request = self.next_request.wrapping_add(1).max(1);
+        self.requests.insert(
+            id,
+            Pending {
+                pid: msg.from_pid,
+                cap_slots: msg.cap_slots.clone(),
+                source: source.clone(),
+                size: snap_size(request.size),
+            },
+        );
+        let vfs = self.tasks.handle();
+        self.tasks.spawn(async move {
+            Done::Read {
+                request: id,
+                result: vfs.read(&source).await,
+            }
+        });
+        // Fails right away if the VFS can't be reached
+        self.finish_tasks();
+        Ok(())
+    }
+
+    /// Handle MSG_THUMBNAIL_DECODED
+    fn handle_decoded(&mut self, msg: &Message) -> Result<(), AppError> {
+        if !TRUSTED_PIDS_FOR_DECODED.contains(&msg.from_pid) {
+            syscall::debug(&format!(
+                "ThumbnailService: SECURITY - DECODED from non-system PID {}",
+                msg.from_pid
+            ));
+            return Ok(());
+        }
+        let Some(decoded) = Decoded::parse(&msg.data) else {
+            syscall::debug("ThumbnailService: Invalid DECODED payload");
+            return Ok(());
+        };
+        self.on_decoded(decoded);
+        Ok(())
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Handle a VFS response to one of our tasks. Returns false if the
+    /// message isn't one.
+    fn handle_vfs_response(&mut self, msg: &Message) -> bool {
+        if !self.tasks.on_message(msg.tag, &msg.data) {
+            return false;
+        }
+        self.finish_tasks();
+        true
+    }
+}
+
+impl JsonResponder for ThumbnailService {
+    const SERVICE_NAME: &'static str = "ThumbnailService";
+}
+
+impl ZeroApp for ThumbnailService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &THUMBNAIL_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("ThumbnailService starting (PID {})", ctx.pid));
+        self.now_ms = ctx.wallclock_ms;
+
+        // Register with init as "thumbnail" service
+        let service_name = "thumbnail";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("ThumbnailService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+
+        let vfs = self.tasks.handle();
+        self.tasks.spawn(load_index(vfs));
+        self.finish_tasks();
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        self.now_ms = ctx.wallclock_ms;
+
+        // VFS responses (Invariant 31 compliant - storage via VFS IPC)
+        if self.handle_vfs_response(&msg) {
+            return Ok(());
+        }
+
+        match msg.tag {
+            // Thumbnail service protocol
+            thumbnail_msg::MSG_THUMBNAIL_GET => self.handle_get(&msg),
+            thumbnail_msg::MSG_THUMBNAIL_DECODED => self.handle_decoded(&msg),
+
+            _ => {
+                syscall::debug(&format!(
+                    "ThumbnailService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("ThumbnailService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+    use zos_vfs::ipc::vfs_msg;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const WEBP: &[u8] = b"RIFF\x24\0\0\0WEBPVP8 ";
+
+    fn get(service: &mut ThumbnailService, pid: u32, json: &str) {
+        let msg = mock_message(
+            thumbnail_msg::MSG_THUMBNAIL_GET,
+            pid,
+            json.as_bytes().to_vec(),
+        );
+        service.handle_get(&msg).unwrap();
+    }
+
+    fn vfs_response(service: &mut ThumbnailService, tag: u32, json: &str) {
+        let msg = mock_message(tag, 4, json.as_bytes().to_vec());
+        assert!(service.handle_vfs_response(&msg));
+    }
+
+    fn read_source(service: &mut ThumbnailService, content: &[u8]) {
+        let json =
+            serde_json::to_string(&serde_json::json!({ "result": { "Ok": content } })).unwrap();
+        vfs_response(service, vfs_msg::MSG_VFS_READ_RESPONSE, &json);
+    }
+
+    fn decoded(service: &mut ThumbnailService, from_pid: u32, id: u32, image: &[u8]) {
+        let mut data = id.to_le_bytes().to_vec();
+        data.push(1);
+        data.extend_from_slice(&128u16.to_le_bytes());
+        data.extend_from_slice(&96u16.to_le_bytes());
+        data.extend_from_slice(image);
+        let msg = mock_message(thumbnail_msg::MSG_THUMBNAIL_DECODED, from_pid, data);
+        service.handle_decoded(&msg).unwrap();
+    }
+
+    #[test]
+    fn test_miss_decodes_once_and_caches() {
+        let mut service = ThumbnailService::default();
+        get(
+            &mut service,
+            10,
+            r#"{"path":"/home/1/Pictures/../Pictures/a.png"}"#,
+        );
+        get(
+            &mut service,
+            11,
+            r#"{"path":"/home/1/Pictures/b.png","size":100}"#,
+        );
+        assert_eq!(service.requests[&1].source, "/home/1/Pictures/a.png");
+        assert_eq!(service.tasks.in_flight(), 2);
+
+        // Both files have the same content, so they share one decode
+        read_source(&mut service, PNG);
+        read_source(&mut service, PNG);
+        assert_eq!(service.decodes.len(), 1);
+        assert_eq!(service.decodes[&1].requests, alloc::vec![1, 2]);
+
+        // Only the supervisor (via Init) can report decodes
+        decoded(&mut service, 10, 1, WEBP);
+        assert!(service.decodes[&1].entry.is_none());
+
+        decoded(&mut service, 1, 1, WEBP);
+        let entry = service.decodes[&1].entry.clone().unwrap();
+        assert_eq!(entry.mime, "image/webp");
+        assert_eq!(entry.hash, content_hash(PNG));
+        assert!(service.index.entries.is_empty());
+
+        vfs_response(
+            &mut service,
+            vfs_msg::MSG_VFS_WRITE_RESPONSE,
+            r#"{"result":{"Ok":null}}"#,
+        );
+        assert!(service.decodes.is_empty());
+        assert!(service.requests.is_empty());
+        assert!(service
+            .index
+            .entries
+            .contains_key(&cache_key(&content_hash(PNG), 128)));
+        // Index save in flight
+        assert_eq!(service.tasks.running(), 1);
+
+        // The next request for the content is a hit
+        vfs_response(
+            &mut service,
+            vfs_msg::MSG_VFS_WRITE_RESPONSE,
+            r#"{"result":{"Ok":null}}"#,
+        );
+        get(&mut service, 12, r#"{"path":"/home/1/c.png","size":128}"#);
+        read_source(&mut service, PNG);
+        assert!(service.requests.is_empty());
+        assert!(service.decodes.is_empty());
+        assert_eq!(service.tasks.running(), 0);
+    }
+
+    #[test]
+    fn test_failures_answer_waiters() {
+        let mut service = ThumbnailService::default();
+        get(&mut service, 10, r#"{"path":"/notes.txt"}"#);
+        read_source(&mut service, b"hello");
+        assert!(service.requests.is_empty());
+        assert!(service.decodes.is_empty());
+
+        get(&mut service, 10, r#"{"path":"/missing.png"}"#);
+        vfs_response(
+            &mut service,
+            vfs_msg::MSG_VFS_READ_RESPONSE,
+            r#"{"result":{"Err":"NotFound"}}"#,
+        );
+        assert!(service.requests.is_empty());
+
+        get(&mut service, 10, r#"{"path":"/a.png"}"#);
+        read_source(&mut service, PNG);
+        let mut data = service
+            .decodes
+            .keys()
+            .next()
+            .unwrap()
+            .to_le_bytes()
+            .to_vec();
+        data.push(0);
+        data.extend_from_slice(b"Corrupt image");
+        let msg = mock_message(thumbnail_msg::MSG_THUMBNAIL_DECODED, 1, data);
+        service.handle_decoded(&msg).unwrap();
+        assert!(service.requests.is_empty());
+        assert!(service.decodes.is_empty());
+    }
+
+    #[test]
+    fn test_rejected_requests() {
+        let mut service = ThumbnailService::default();
+        get(&mut service, 10, r#"{"path":"relative.png"}"#);
+        get(&mut service, 10, r#"{"path":"/../etc.png"}"#);
+        get(&mut service, 10, r#"{"size":64}"#);
+        assert!(service.requests.is_empty());
+
+        for i in 0..MAX_REQUESTS + 1 {
+            get(&mut service, 10, &format!(r#"{{"path":"/{}.png"}}"#, i));
+        }
+        assert_eq!(service.requests.len(), MAX_REQUESTS);
+    }
+
+    #[test]
+    fn test_loaded_index_keeps_new_thumbnails() {
+        let mut service = ThumbnailService::default();
+        let entry = |hash: &str| CacheEntry {
+            hash: String::from(hash),
+            size: 64,
+            width: 64,
+            height: 64,
+            mime: String::from("image/png"),
+            ext: String::from("png"),
+            bytes: 10,
+            last_used_ms: 1,
+        };
+        service.index.insert(cache_key("new", 64), entry("new"));
+        let mut loaded = ThumbnailIndex::default();
+        loaded.insert(cache_key("old", 64), entry("old"));
+
+        service.on_loaded(Some(loaded));
+        assert_eq!(service.index.entries.len(), 2);
+        // The merged index is saved
+        assert_eq!(service.tasks.running(), 1);
+    }
+}
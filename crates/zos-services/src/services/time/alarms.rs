@@ -0,0 +1,451 @@
+//! Alarms and countdowns
+//!
+//! Apps set alarms for a time or after a countdown, once or repeating
+//! daily, on weekdays or weekly. The TimeService keeps them in
+//! `/system/settings/alarms.json` and re-arms them at boot, so a Clock app
+//! needs no daemon of its own: a due alarm is shown as a desktop
+//! notification and sent as MSG_TIME_ALARM to the app if it is running.
+//!
+//! Alarms belong to the process name that set them rather than its PID, so
+//! the app sees its alarms again after a restart. A repeating alarm keeps
+//! its local time of day in the zone it was set in, across daylight saving
+//! changes. Alarms that came due while nothing was running fire once when
+//! the service starts.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use super::tz::{weekday, TimeZone};
+
+/// Storage path for alarms
+pub const ALARMS_PATH: &str = "/system/settings/alarms.json";
+
+/// Maximum alarms in total (Rule 11: resource limits)
+pub const MAX_ALARMS: usize = 128;
+
+/// Maximum alarms per owner
+pub const MAX_ALARMS_PER_OWNER: usize = 16;
+
+/// Maximum label length in bytes
+pub const MAX_LABEL_LEN: usize = 128;
+
+/// Milliseconds per minute.
+const MS_PER_MINUTE: i64 = 60_000;
+
+/// Milliseconds per day.
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// How an alarm repeats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Repeat {
+    /// Every day
+    Daily,
+    /// Monday to Friday
+    Weekdays,
+    /// Every week on the same day
+    Weekly,
+}
+
+impl Repeat {
+    /// Days between candidate occurrences.
+    fn step_days(self) -> i64 {
+        match self {
+            Repeat::Weekly => 7,
+            Repeat::Daily | Repeat::Weekdays => 1,
+        }
+    }
+
+    /// Whether the alarm fires on `weekday` (0 = Sunday).
+    fn fires_on(self, weekday: u32) -> bool {
+        self != Repeat::Weekdays || (1..=5).contains(&weekday)
+    }
+}
+
+/// An armed alarm.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alarm {
+    pub id: u32,
+    /// Name of the process that set it
+    pub owner: String,
+    #[serde(default)]
+    pub label: String,
+    /// Next time it fires, ms since the Unix epoch
+    pub fire_at_ms: u64,
+    #[serde(default)]
+    pub repeat: Option<Repeat>,
+    /// Zone whose local time a repeating alarm keeps
+    pub timezone: String,
+}
+
+/// Payload of MSG_TIME_SET_ALARM.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SetAlarmRequest {
+    /// Fire at this time, ms since the Unix epoch
+    #[serde(default)]
+    pub at_ms: Option<u64>,
+    /// Fire this many ms from now
+    #[serde(default)]
+    pub in_ms: Option<u64>,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub repeat: Option<Repeat>,
+}
+
+/// Payload of MSG_TIME_CANCEL_ALARM.
+#[derive(Clone, Debug, Deserialize)]
+pub struct CancelAlarmRequest {
+    pub id: u32,
+}
+
+/// Payload of MSG_TIME_LIST_ALARMS_RESPONSE.
+#[derive(Clone, Debug, Serialize)]
+pub struct AlarmList {
+    pub alarms: Vec<Alarm>,
+}
+
+/// A desktop notification, sent to the supervisor as `NOTIFY:SHOW:`.
+#[derive(Clone, Debug, Serialize)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    /// Process name the notification is on behalf of
+    pub source: String,
+    /// Replaces an earlier notification with the same tag
+    pub tag: String,
+}
+
+/// All armed alarms.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AlarmTable {
+    alarms: BTreeMap<u32, Alarm>,
+    next_id: u32,
+}
+
+/// Stored form of [`AlarmTable`]
+#[derive(Default, Serialize, Deserialize)]
+struct SavedAlarms {
+    #[serde(default)]
+    alarms: Vec<Alarm>,
+}
+
+impl AlarmTable {
+    /// Serialize to JSON bytes
+    pub fn to_json(&self) -> Vec<u8> {
+        let saved = SavedAlarms {
+            alarms: self.alarms.values().cloned().collect(),
+        };
+        serde_json::to_vec(&saved).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        let saved: SavedAlarms = serde_json::from_slice(data).ok()?;
+        let mut table = Self::default();
+        for alarm in saved.alarms.into_iter().take(MAX_ALARMS) {
+            table.next_id = table.next_id.max(alarm.id.wrapping_add(1));
+            table.alarms.insert(alarm.id, alarm);
+        }
+        Some(table)
+    }
+
+    pub fn len(&self) -> usize {
+        self.alarms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alarms.is_empty()
+    }
+
+    /// Arm an alarm for `owner`, whose repeats keep local time in `zone`.
+    pub fn add(
+        &mut self,
+        owner: &str,
+        request: SetAlarmRequest,
+        zone: &TimeZone,
+        now_ms: u64,
+    ) -> Result<Alarm, String> {
+        let fire_at_ms = match (request.at_ms, request.in_ms) {
+            (Some(at), None) => at,
+            (None, Some(delay)) => now_ms.saturating_add(delay),
+            _ => return Err(String::from("Exactly one of at_ms and in_ms is required")),
+        };
+        if request.label.len() > MAX_LABEL_LEN {
+            return Err(format!("Label too long (max {} bytes)", MAX_LABEL_LEN));
+        }
+        if self.alarms.len() >= MAX_ALARMS {
+            return Err(format!("Alarm limit reached (max {})", MAX_ALARMS));
+        }
+        if self.alarms.values().filter(|a| a.owner == owner).count() >= MAX_ALARMS_PER_OWNER {
+            return Err(format!(
+                "Alarm limit reached for {} (max {})",
+                owner, MAX_ALARMS_PER_OWNER
+            ));
+        }
+
+        let fire_at_ms = match request.repeat {
+            Some(repeat)
+                if fire_at_ms <= now_ms || !repeat.fires_on(local_weekday(zone, fire_at_ms)) =>
+            {
+                next_occurrence(fire_at_ms, repeat, zone, now_ms)
+            }
+            Some(_) => fire_at_ms,
+            None if fire_at_ms <= now_ms => {
+                return Err(String::from("Alarm time is in the past"));
+            }
+            None => fire_at_ms,
+        };
+
+        let alarm = Alarm {
+            id: self.alloc_id(),
+            owner: String::from(owner),
+            label: request.label,
+            fire_at_ms,
+            repeat: request.repeat,
+            timezone: zone.name.clone(),
+        };
+        self.alarms.insert(alarm.id, alarm.clone());
+        Ok(alarm)
+    }
+
+    fn alloc_id(&mut self) -> u32 {
+        // At most MAX_ALARMS IDs are taken, so this finds one quickly
+        while self.alarms.contains_key(&self.next_id) || self.next_id == 0 {
+            self.next_id = self.next_id.wrapping_add(1);
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        id
+    }
+
+    /// Cancel `owner`'s alarm `id`.
+    pub fn cancel(&mut self, owner: &str, id: u32) -> Result<Alarm, String> {
+        match self.alarms.get(&id) {
+            Some(alarm) if alarm.owner == owner => Ok(self.alarms.remove(&id).unwrap()),
+            // Another owner's alarm looks the same as a missing one
+            _ => Err(format!("No alarm {}", id)),
+        }
+    }
+
+    /// `owner`'s alarms, soonest first.
+    pub fn list(&self, owner: &str) -> Vec<Alarm> {
+        let mut alarms: Vec<Alarm> = self
+            .alarms
+            .values()
+            .filter(|a| a.owner == owner)
+            .cloned()
+            .collect();
+        alarms.sort_by_key(|a| (a.fire_at_ms, a.id));
+        alarms
+    }
+
+    /// Take the alarms due at `now_ms`, re-arming repeating ones for their
+    /// next occurrence after now. Each is returned once, with `fire_at_ms`
+    /// the time it came due.
+    pub fn take_due(&mut self, now_ms: u64) -> Vec<Alarm> {
+        let due: Vec<u32> = self
+            .alarms
+            .values()
+            .filter(|a| a.fire_at_ms <= now_ms)
+            .map(|a| a.id)
+            .collect();
+        let mut fired = Vec::with_capacity(due.len());
+        for id in due {
+            let Some(alarm) = self.alarms.remove(&id) else {
+                continue;
+            };
+            if let Some(repeat) = alarm.repeat {
+                // The zone came from the table when the alarm was set;
+                // fall back to UTC should it ever go away
+                let zone = TimeZone::lookup(&alarm.timezone).unwrap_or_else(TimeZone::utc);
+                let mut next = alarm.clone();
+                next.fire_at_ms = next_occurrence(alarm.fire_at_ms, repeat, &zone, now_ms);
+                self.alarms.insert(id, next);
+            }
+            fired.push(alarm);
+        }
+        fired
+    }
+
+    /// Take on alarms loaded from storage, keeping any armed since start.
+    pub fn merge(&mut self, loaded: AlarmTable) {
+        for (id, alarm) in loaded.alarms {
+            if self.alarms.len() >= MAX_ALARMS {
+                break;
+            }
+            if self.alarms.contains_key(&id) {
+                // Armed before the load finished; give the stored one a new ID
+                let mut alarm = alarm;
+                alarm.id = self.alloc_id();
+                self.alarms.insert(alarm.id, alarm);
+            } else {
+                self.alarms.insert(id, alarm);
+            }
+        }
+        self.next_id = self.next_id.max(loaded.next_id);
+    }
+}
+
+/// Day of the week (0 = Sunday) of `utc_ms` in `zone`.
+fn local_weekday(zone: &TimeZone, utc_ms: u64) -> u32 {
+    let utc_ms = utc_ms as i64;
+    let local = utc_ms + zone.offset_at(utc_ms).minutes as i64 * MS_PER_MINUTE;
+    weekday(local.div_euclid(MS_PER_DAY))
+}
+
+/// First occurrence of a repeating alarm after `after_ms`, keeping the
+/// local time of day of `fire_at_ms`.
+fn next_occurrence(fire_at_ms: u64, repeat: Repeat, zone: &TimeZone, after_ms: u64) -> u64 {
+    let fire_at = fire_at_ms as i64;
+    let after = after_ms as i64;
+    let mut local = fire_at + zone.offset_at(fire_at).minutes as i64 * MS_PER_MINUTE;
+
+    // Skip whole weeks of missed occurrences; the weekday is unchanged
+    let week = 7 * MS_PER_DAY;
+    if after - fire_at > week {
+        local += (after - fire_at) / week * week - week;
+    }
+    loop {
+        local += repeat.step_days() * MS_PER_DAY;
+        if !repeat.fires_on(weekday(local.div_euclid(MS_PER_DAY))) {
+            continue;
+        }
+        // Local -> UTC, using the offset in effect around that time
+        let guess = local - zone.offset_at(local).minutes as i64 * MS_PER_MINUTE;
+        let utc = local - zone.offset_at(guess).minutes as i64 * MS_PER_MINUTE;
+        if utc > after {
+            return utc as u64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tz::days_from_civil;
+    use super::*;
+
+    /// ms since the epoch of a UTC date and time.
+    fn utc(year: i64, month: u32, day: u32, hour: i64, minute: i64) -> u64 {
+        (days_from_civil(year, month, day) * MS_PER_DAY + hour * 3_600_000 + minute * 60_000) as u64
+    }
+
+    fn at(at_ms: u64, repeat: Option<Repeat>) -> SetAlarmRequest {
+        SetAlarmRequest {
+            at_ms: Some(at_ms),
+            repeat,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_countdown_fires_once() {
+        let utc_zone = TimeZone::utc();
+        let mut table = AlarmTable::default();
+        let now = utc(2024, 7, 4, 12, 0);
+        let request = SetAlarmRequest {
+            in_ms: Some(300_000),
+            label: String::from("Tea"),
+            ..Default::default()
+        };
+        let alarm = table.add("clock", request, &utc_zone, now).unwrap();
+        assert_eq!(alarm.fire_at_ms, utc(2024, 7, 4, 12, 5));
+
+        assert!(table.take_due(utc(2024, 7, 4, 12, 4)).is_empty());
+        let fired = table.take_due(utc(2024, 7, 4, 12, 5));
+        assert_eq!(fired, [alarm]);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_bad_requests() {
+        let zone = TimeZone::utc();
+        let mut table = AlarmTable::default();
+        let now = utc(2024, 7, 4, 12, 0);
+        assert!(table
+            .add("clock", SetAlarmRequest::default(), &zone, now)
+            .is_err());
+        assert!(table.add("clock", at(now - 1, None), &zone, now).is_err());
+
+        for _ in 0..MAX_ALARMS_PER_OWNER {
+            table.add("clock", at(now + 1, None), &zone, now).unwrap();
+        }
+        assert!(table.add("clock", at(now + 1, None), &zone, now).is_err());
+        assert!(table.add("timer", at(now + 1, None), &zone, now).is_ok());
+    }
+
+    #[test]
+    fn test_daily_alarm_keeps_local_time_across_dst() {
+        let ny = TimeZone::lookup("America/New_York").unwrap();
+        let mut table = AlarmTable::default();
+        // 07:00 EST on Saturday 2024-03-09
+        let first = utc(2024, 3, 9, 12, 0);
+        let alarm = table
+            .add("clock", at(first, Some(Repeat::Daily)), &ny, first - 1)
+            .unwrap();
+        assert_eq!(alarm.fire_at_ms, first);
+
+        table.take_due(first);
+        // 07:00 EDT on Sunday, after clocks went forward
+        assert_eq!(table.list("clock")[0].fire_at_ms, utc(2024, 3, 10, 11, 0));
+    }
+
+    #[test]
+    fn test_weekdays_skip_weekend_and_missed_fire_once() {
+        let zone = TimeZone::utc();
+        let mut table = AlarmTable::default();
+        // Saturday 2024-07-06 08:00 rolls forward to Monday
+        let saturday = utc(2024, 7, 6, 8, 0);
+        let alarm = table
+            .add(
+                "clock",
+                at(saturday, Some(Repeat::Weekdays)),
+                &zone,
+                saturday - 1,
+            )
+            .unwrap();
+        assert_eq!(alarm.fire_at_ms, utc(2024, 7, 8, 8, 0));
+
+        // Down for three weeks: one firing, then the next weekday
+        let fired = table.take_due(utc(2024, 7, 31, 9, 0));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(table.list("clock")[0].fire_at_ms, utc(2024, 8, 1, 8, 0));
+    }
+
+    #[test]
+    fn test_cancel_and_list_are_per_owner() {
+        let zone = TimeZone::utc();
+        let mut table = AlarmTable::default();
+        let later = table.add("clock", at(200, None), &zone, 0).unwrap();
+        let sooner = table.add("clock", at(100, None), &zone, 0).unwrap();
+        let other = table.add("timer", at(100, None), &zone, 0).unwrap();
+
+        assert_eq!(table.list("clock"), [sooner.clone(), later]);
+        assert!(table.cancel("clock", other.id).is_err());
+        assert_eq!(table.cancel("clock", sooner.id).unwrap(), sooner);
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn test_json_round_trip_and_merge() {
+        let zone = TimeZone::lookup("Europe/Paris").unwrap();
+        let mut stored = AlarmTable::default();
+        stored
+            .add("clock", at(100, Some(Repeat::Weekly)), &zone, 0)
+            .unwrap();
+        let loaded = AlarmTable::from_json(&stored.to_json()).unwrap();
+        assert_eq!(loaded, stored);
+
+        // An alarm armed before the load keeps its ID
+        let mut table = AlarmTable::default();
+        let early = table.add("timer", at(50, None), &zone, 0).unwrap();
+        table.merge(loaded);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.list("timer"), [early]);
+        assert_eq!(table.list("clock")[0].repeat, Some(Repeat::Weekly));
+        assert!(AlarmTable::from_json(b"not json").is_none());
+    }
+}
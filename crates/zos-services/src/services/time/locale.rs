@@ -0,0 +1,368 @@
+//! Locale-aware date and time formatting
+//!
+//! Each supported locale fixes the numeric date order and separator, the
+//! default clock (12h or 24h), and the names used in long dates. A tag
+//! that isn't listed falls back to the first locale with its language
+//! (`en-AU` → `en-GB`, `de-AT` → `de-DE`).
+
+use alloc::format;
+use alloc::string::String;
+
+use super::tz::{civil_from_days, weekday};
+
+/// Milliseconds per day.
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// Order of the numeric date fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DateOrder {
+    /// 03/05/2024
+    Mdy,
+    /// 05/03/2024
+    Dmy,
+    /// 2024/03/05
+    Ymd,
+}
+
+/// Formatting conventions of one locale.
+#[derive(Debug)]
+pub struct Locale {
+    /// BCP 47 tag
+    pub tag: &'static str,
+    order: DateOrder,
+    separator: char,
+    /// Whether the locale uses a 24-hour clock by default
+    pub default_24h: bool,
+    am_pm: [&'static str; 2],
+    months: [&'static str; 12],
+    /// Sunday first
+    weekdays: [&'static str; 7],
+}
+
+const EN_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const EN_WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+const CJK_MONTHS: [&str; 12] = [
+    "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+];
+
+const LOCALES: &[Locale] = &[
+    Locale {
+        tag: "en-US",
+        order: DateOrder::Mdy,
+        separator: '/',
+        default_24h: false,
+        am_pm: ["AM", "PM"],
+        months: EN_MONTHS,
+        weekdays: EN_WEEKDAYS,
+    },
+    Locale {
+        tag: "en-GB",
+        order: DateOrder::Dmy,
+        separator: '/',
+        default_24h: true,
+        am_pm: ["am", "pm"],
+        months: EN_MONTHS,
+        weekdays: EN_WEEKDAYS,
+    },
+    Locale {
+        tag: "de-DE",
+        order: DateOrder::Dmy,
+        separator: '.',
+        default_24h: true,
+        am_pm: ["AM", "PM"],
+        months: [
+            "Januar",
+            "Februar",
+            "März",
+            "April",
+            "Mai",
+            "Juni",
+            "Juli",
+            "August",
+            "September",
+            "Oktober",
+            "November",
+            "Dezember",
+        ],
+        weekdays: [
+            "Sonntag",
+            "Montag",
+            "Dienstag",
+            "Mittwoch",
+            "Donnerstag",
+            "Freitag",
+            "Samstag",
+        ],
+    },
+    Locale {
+        tag: "fr-FR",
+        order: DateOrder::Dmy,
+        separator: '/',
+        default_24h: true,
+        am_pm: ["AM", "PM"],
+        months: [
+            "janvier",
+            "février",
+            "mars",
+            "avril",
+            "mai",
+            "juin",
+            "juillet",
+            "août",
+            "septembre",
+            "octobre",
+            "novembre",
+            "décembre",
+        ],
+        weekdays: [
+            "dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi",
+        ],
+    },
+    Locale {
+        tag: "es-ES",
+        order: DateOrder::Dmy,
+        separator: '/',
+        default_24h: true,
+        am_pm: ["a. m.", "p. m."],
+        months: [
+            "enero",
+            "febrero",
+            "marzo",
+            "abril",
+            "mayo",
+            "junio",
+            "julio",
+            "agosto",
+            "septiembre",
+            "octubre",
+            "noviembre",
+            "diciembre",
+        ],
+        weekdays: [
+            "domingo",
+            "lunes",
+            "martes",
+            "miércoles",
+            "jueves",
+            "viernes",
+            "sábado",
+        ],
+    },
+    Locale {
+        tag: "ja-JP",
+        order: DateOrder::Ymd,
+        separator: '/',
+        default_24h: true,
+        am_pm: ["午前", "午後"],
+        months: CJK_MONTHS,
+        weekdays: [
+            "日曜日",
+            "月曜日",
+            "火曜日",
+            "水曜日",
+            "木曜日",
+            "金曜日",
+            "土曜日",
+        ],
+    },
+    Locale {
+        tag: "zh-CN",
+        order: DateOrder::Ymd,
+        separator: '/',
+        default_24h: true,
+        am_pm: ["上午", "下午"],
+        months: CJK_MONTHS,
+        weekdays: [
+            "星期日",
+            "星期一",
+            "星期二",
+            "星期三",
+            "星期四",
+            "星期五",
+            "星期六",
+        ],
+    },
+];
+
+/// Default locale of the system settings.
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Broken-down local time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct DateTimeFields {
+    pub year: i64,
+    /// 1-12
+    pub month: u32,
+    /// 1-31
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// 0 = Sunday
+    pub weekday: u32,
+}
+
+impl DateTimeFields {
+    /// Break down `local_ms`, ms since the epoch in local time.
+    pub fn from_local_ms(local_ms: i64) -> Self {
+        let days = local_ms.div_euclid(MS_PER_DAY);
+        let seconds = local_ms.rem_euclid(MS_PER_DAY) / 1000;
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            hour: (seconds / 3600) as u32,
+            minute: (seconds / 60 % 60) as u32,
+            second: (seconds % 60) as u32,
+            weekday: weekday(days),
+        }
+    }
+}
+
+impl Locale {
+    /// Look up `tag` (case-insensitive, `_` accepted for `-`), falling back
+    /// to a locale of the same language.
+    pub fn lookup(tag: &str) -> Option<&'static Locale> {
+        let tag = tag.replace('_', "-");
+        if let Some(locale) = LOCALES.iter().find(|l| l.tag.eq_ignore_ascii_case(&tag)) {
+            return Some(locale);
+        }
+        let language = tag.split('-').next()?;
+        if language.is_empty() {
+            return None;
+        }
+        // en-US and en-GB share a language; any other English region
+        // follows British conventions
+        if language.eq_ignore_ascii_case("en") && tag.contains('-') {
+            return LOCALES.iter().find(|l| l.tag == "en-GB");
+        }
+        LOCALES.iter().find(|l| {
+            l.tag
+                .split('-')
+                .next()
+                .is_some_and(|lang| lang.eq_ignore_ascii_case(language))
+        })
+    }
+
+    /// Numeric date, e.g. `03/05/2024`, `05.03.2024` or `2024/03/05`.
+    pub fn format_date(&self, t: &DateTimeFields) -> String {
+        let sep = self.separator;
+        match self.order {
+            DateOrder::Mdy => format!("{:02}{sep}{:02}{sep}{}", t.month, t.day, t.year),
+            DateOrder::Dmy => format!("{:02}{sep}{:02}{sep}{}", t.day, t.month, t.year),
+            DateOrder::Ymd => format!("{}{sep}{:02}{sep}{:02}", t.year, t.month, t.day),
+        }
+    }
+
+    /// Date with weekday and month names, e.g. `Tuesday, March 5, 2024`.
+    pub fn format_long_date(&self, t: &DateTimeFields) -> String {
+        let weekday = self.weekdays[t.weekday as usize % 7];
+        let month = self.months[(t.month as usize + 11) % 12];
+        match self.tag {
+            "ja-JP" | "zh-CN" => format!("{}年{}{}日 {}", t.year, month, t.day, weekday),
+            "de-DE" => format!("{}, {}. {} {}", weekday, t.day, month, t.year),
+            "es-ES" => format!("{}, {} de {} de {}", weekday, t.day, month, t.year),
+            _ if self.order == DateOrder::Mdy => {
+                format!("{}, {} {}, {}", weekday, month, t.day, t.year)
+            }
+            _ => format!("{} {} {} {}", weekday, t.day, month, t.year),
+        }
+    }
+
+    /// Time of day, e.g. `3:04 PM` or `15:04`, with seconds if asked.
+    pub fn format_time(&self, t: &DateTimeFields, use_24h: bool, seconds: bool) -> String {
+        let secs = if seconds {
+            format!(":{:02}", t.second)
+        } else {
+            String::new()
+        };
+        if use_24h {
+            return format!("{:02}:{:02}{}", t.hour, t.minute, secs);
+        }
+        let hour = match t.hour % 12 {
+            0 => 12,
+            h => h,
+        };
+        let marker = self.am_pm[usize::from(t.hour >= 12)];
+        if self.order == DateOrder::Ymd {
+            // CJK locales put the marker first
+            format!("{}{}:{:02}{}", marker, hour, t.minute, secs)
+        } else {
+            format!("{}:{:02}{} {}", hour, t.minute, secs, marker)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T: DateTimeFields = DateTimeFields {
+        year: 2024,
+        month: 3,
+        day: 5,
+        hour: 15,
+        minute: 4,
+        second: 9,
+        weekday: 2,
+    };
+
+    #[test]
+    fn test_lookup_and_fallback() {
+        assert_eq!(Locale::lookup("en_us").unwrap().tag, "en-US");
+        assert_eq!(Locale::lookup("en-AU").unwrap().tag, "en-GB");
+        assert_eq!(Locale::lookup("en").unwrap().tag, "en-US");
+        assert_eq!(Locale::lookup("de-AT").unwrap().tag, "de-DE");
+        assert!(Locale::lookup("xx-YY").is_none());
+        assert!(Locale::lookup("").is_none());
+    }
+
+    #[test]
+    fn test_formats() {
+        let us = Locale::lookup("en-US").unwrap();
+        assert_eq!(us.format_date(&T), "03/05/2024");
+        assert_eq!(us.format_long_date(&T), "Tuesday, March 5, 2024");
+        assert_eq!(us.format_time(&T, false, false), "3:04 PM");
+        assert_eq!(us.format_time(&T, true, true), "15:04:09");
+
+        let de = Locale::lookup("de-DE").unwrap();
+        assert_eq!(de.format_date(&T), "05.03.2024");
+        assert_eq!(de.format_long_date(&T), "Dienstag, 5. März 2024");
+
+        let ja = Locale::lookup("ja-JP").unwrap();
+        assert_eq!(ja.format_date(&T), "2024/03/05");
+        assert_eq!(ja.format_long_date(&T), "2024年3月5日 火曜日");
+        assert_eq!(ja.format_time(&T, false, false), "午後3:04");
+    }
+
+    #[test]
+    fn test_fields_from_local_ms() {
+        // 2024-03-05 15:04:09
+        assert_eq!(DateTimeFields::from_local_ms(1_709_651_049_000), T);
+        let before_epoch = DateTimeFields::from_local_ms(-1000);
+        assert_eq!((before_epoch.year, before_epoch.hour), (1969, 23));
+        assert_eq!(before_epoch.weekday, 3);
+    }
+}
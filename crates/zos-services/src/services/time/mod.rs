@@ -2,22 +2,40 @@
 //!
 //! The TimeService manages time-related settings. It:
 //! - Stores user time format preferences (12h/24h)
-//! - Stores user timezone preferences
+//! - Stores user timezone and locale preferences
 //! - Persists settings via VFS service IPC (async pattern)
+//! - Formats timestamps for any supported timezone and locale, so apps
+//!   don't bundle their own tz database
+//! - Keeps per-process timezone/locale overrides (e.g. a world clock)
+//!   separate from the system settings
+//! - Keeps alarms and countdowns for apps, persisted via VFS, and fires them
+//!   whether or not the app that set them is running
 //!
 //! # Safety Invariants
 //!
 //! **Success means:**
 //! - GET: Settings returned to client (from cache or storage)
 //! - SET: Settings written to storage AND cache updated AND success response sent
+//! - SET_OVERRIDE: Timezone/locale validated AND stored for the caller only
+//! - CONVERT: Local time computed from the request, then the caller's
+//!   override, then the system settings
+//! - SET_ALARM: Alarm validated AND armed AND a write of the alarm store started
+//! - Alarm due: notification shown AND MSG_TIME_ALARM sent to every running
+//!   process of its owner AND repeating alarms re-armed
 //!
 //! **Acceptable partial failure:**
 //! - Storage read fails → return default settings (fail-open for read-only)
 //! - Cache may be stale if storage write succeeds but cache update fails
+//! - CONVERT before the settings load → formatted with the defaults
+//! - Overrides are kept in memory only and lost when the service restarts
+//! - Alarm store write fails → alarms stay armed until the service restarts
+//! - Alarms due before the store loads fire once it has
 //!
 //! **Forbidden:**
 //! - Returning success for SET before storage write completes
 //! - Allowing unauthorized processes to modify system time settings
+//! - A process's override affecting the system settings or another process
+//! - A process seeing or cancelling alarms set by another app
 //! - Unbounded pending operations (DoS vector)
 //!
 //! # Protocol
@@ -26,6 +44,23 @@
 //!
 //! - `MSG_GET_TIME_SETTINGS (0x8100)`: Get current time settings
 //! - `MSG_SET_TIME_SETTINGS (0x8102)`: Update time settings
+//! - `MSG_TIME_SET_OVERRIDE (0x8104)`: Set the caller's own timezone/locale
+//! - `MSG_TIME_CONVERT (0x8106)`: Format a timestamp as local time
+//! - `MSG_TIME_SET_ALARM (0x8108)`: Set an alarm or countdown
+//! - `MSG_TIME_CANCEL_ALARM (0x810A)`: Cancel one of the caller's alarms
+//! - `MSG_TIME_LIST_ALARMS (0x810C)`: List the caller's alarms
+//! - `MSG_TIME_ALARM (0x810E)`: An alarm fired (sent to its app)
+//!
+//! # Time Zones and Locales
+//!
+//! Zones come from a built-in table of current rules (see [`tz`]); fixed
+//! offsets such as `UTC+05:30` are accepted too. Locales set the date
+//! order, default clock and month/weekday names (see [`locale`]).
+//!
+//! # Alarms
+//!
+//! See [`alarms`]. Due alarms are checked on every update and shown through
+//! the supervisor as `NOTIFY:SHOW:{hex_json}`.
 //!
 //! # Storage Access
 //!
@@ -34,6 +69,10 @@
 
 extern crate alloc;
 
+pub(crate) mod alarms;
+mod locale;
+pub(crate) mod tz;
+
 use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
@@ -42,6 +81,11 @@ use zos_apps::syscall;
 use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
 use zos_vfs::async_client;
 use zos_vfs::ipc::vfs_msg;
+use alarms::{
+    Alarm, AlarmList, AlarmTable, CancelAlarmRequest, Notification, SetAlarmRequest, ALARMS_PATH,
+};
+use locale::{DateTimeFields, Locale, DEFAULT_LOCALE};
+use tz::{format_offset, TimeZone};
 
 // =============================================================================
 // IPC Message Tags (re-exported from zos-ipc for single source of truth)
@@ -64,6 +108,11 @@ fn default_timezone() -> String {
     String::from("UTC")
 }
 
+/// Default locale for time settings
+fn default_locale() -> String {
+    String::from(DEFAULT_LOCALE)
+}
+
 /// Time settings that can be persisted
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct TimeSettings {
@@ -73,6 +122,9 @@ pub struct TimeSettings {
     /// Timezone identifier (e.g., "America/New_York", "UTC")
     #[serde(default = "default_timezone")]
     pub timezone: String,
+    /// Locale tag for date formatting (e.g., "en-US", "de-DE")
+    #[serde(default = "default_locale")]
+    pub locale: String,
 }
 
 impl Default for TimeSettings {
@@ -80,6 +132,7 @@ impl Default for TimeSettings {
         Self {
             time_format_24h: false,
             timezone: default_timezone(),
+            locale: default_locale(),
         }
     }
 }
@@ -95,8 +148,8 @@ impl TimeSettings {
         serde_json::to_vec(self).unwrap_or_else(|_| {
             // Fallback to manual serialization if serde fails
             format!(
-                r#"{{"time_format_24h":{},"timezone":"{}"}}"#,
-                self.time_format_24h, self.timezone
+                r#"{{"time_format_24h":{},"timezone":"{}","locale":"{}"}}"#,
+                self.time_format_24h, self.timezone, self.locale
             )
             .into_bytes()
         })
@@ -108,6 +161,89 @@ impl TimeSettings {
     }
 }
 
+/// A process's own time settings; absent fields use the system settings.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+pub struct TimeOverride {
+    /// Timezone identifier or fixed offset (e.g., "Asia/Tokyo", "UTC+05:30")
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Locale tag (e.g., "ja-JP")
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Use 24-hour time format; defaults to the locale's if it is set
+    #[serde(default)]
+    pub time_format_24h: Option<bool>,
+}
+
+impl TimeOverride {
+    fn is_empty(&self) -> bool {
+        self.timezone.is_none() && self.locale.is_none() && self.time_format_24h.is_none()
+    }
+}
+
+/// Payload of MSG_TIME_CONVERT.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+struct ConvertRequest {
+    /// ms since the Unix epoch; now if absent
+    #[serde(default)]
+    timestamp_ms: Option<u64>,
+    /// Settings for this conversion only
+    #[serde(flatten)]
+    settings: TimeOverride,
+    /// Include seconds in `time`
+    #[serde(default)]
+    seconds: bool,
+}
+
+/// A timestamp as local time, the payload of MSG_TIME_CONVERT_RESPONSE.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct ConvertedTime {
+    /// ms since the Unix epoch
+    pub timestamp_ms: u64,
+    /// Timezone used
+    pub timezone: String,
+    /// Locale used
+    pub locale: String,
+    /// Minutes east of UTC
+    pub offset_minutes: i32,
+    /// Offset as "+09:00"
+    pub utc_offset: String,
+    /// Zone abbreviation (e.g., "JST", "EDT")
+    pub abbreviation: String,
+    /// Whether daylight saving time is in effect
+    pub dst: bool,
+    /// Broken-down local time
+    pub fields: DateTimeFields,
+    /// Numeric date (e.g., "03/05/2024")
+    pub date: String,
+    /// Date with weekday and month names
+    pub long_date: String,
+    /// Time of day (e.g., "3:04 PM")
+    pub time: String,
+}
+
+/// Name of process `pid`, which owns the alarms it sets.
+fn process_name(pid: u32) -> String {
+    syscall::list_processes()
+        .into_iter()
+        .find(|p| p.pid == pid)
+        .map(|p| p.name)
+        .unwrap_or_else(|| format!("pid-{}", pid))
+}
+
+/// Validate an override, replacing its names with the canonical ones.
+fn canonicalize(mut o: TimeOverride) -> Result<TimeOverride, String> {
+    if let Some(name) = &o.timezone {
+        let zone = TimeZone::lookup(name).ok_or_else(|| format!("Unknown timezone: {}", name))?;
+        o.timezone = Some(zone.name);
+    }
+    if let Some(tag) = &o.locale {
+        let locale = Locale::lookup(tag).ok_or_else(|| format!("Unknown locale: {}", tag))?;
+        o.locale = Some(String::from(locale.tag));
+    }
+    Ok(o)
+}
+
 // =============================================================================
 // Pending VFS Operations
 // =============================================================================
@@ -131,6 +267,10 @@ enum PendingOp {
     },
     /// Initial load of settings on startup
     InitialLoad,
+    /// Load of the alarm store on startup
+    LoadAlarms,
+    /// Writing the alarm store after a change
+    SaveAlarms,
 }
 
 /// Operation type for matching responses.
@@ -153,6 +293,9 @@ use alloc::collections::BTreeMap;
 /// Maximum number of pending VFS operations (DoS protection per Rule 11)
 const MAX_PENDING_OPS: usize = 32;
 
+/// Maximum processes with an override (Rule 11)
+const MAX_OVERRIDES: usize = 64;
+
 /// System service PIDs that are trusted for time settings operations.
 /// - PID 0: Supervisor
 /// - PID 1: Init
@@ -173,19 +316,26 @@ pub struct TimeService {
     next_request_id: u32,
     /// Whether settings have been loaded from storage
     settings_loaded: bool,
+    /// Per-process overrides by PID
+    overrides: BTreeMap<u32, TimeOverride>,
+    /// Armed alarms
+    alarms: AlarmTable,
+    /// Whether the alarm store has been loaded; until then nothing is
+    /// saved (it would overwrite the store) or fired
+    alarms_loaded: bool,
 }
 
 impl Default for TimeService {
     fn default() -> Self {
         Self {
             registered: false,
-            settings: TimeSettings {
-                time_format_24h: false,
-                timezone: String::from("UTC"),
-            },
+            settings: TimeSettings::default(),
             pending_ops: BTreeMap::new(),
             next_request_id: 1,
             settings_loaded: false,
+            overrides: BTreeMap::new(),
+            alarms: AlarmTable::default(),
+            alarms_loaded: false,
         }
     }
 }
@@ -414,6 +564,303 @@ impl TimeService {
         ).map(|_| ())
     }
 
+    /// Handle MSG_TIME_SET_OVERRIDE
+    fn handle_set_override(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = time_msg::MSG_TIME_SET_OVERRIDE_RESPONSE;
+        let requested: TimeOverride = if msg.data.is_empty() {
+            TimeOverride::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(o) => o,
+                Err(_) => {
+                    return self.send_tagged_error(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid override format: JSON parse failed",
+                    );
+                }
+            }
+        };
+
+        if requested.is_empty() {
+            self.overrides.remove(&msg.from_pid);
+        } else {
+            let canonical = match canonicalize(requested) {
+                Ok(o) => o,
+                Err(e) => return self.send_tagged_error(msg.from_pid, &msg.cap_slots, tag, &e),
+            };
+            if !self.overrides.contains_key(&msg.from_pid) && !self.make_room_for_override() {
+                return self.send_tagged_error(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Service busy: override limit reached",
+                );
+            }
+            syscall::debug(&format!(
+                "TimeService: PID {} override {:?}",
+                msg.from_pid, canonical
+            ));
+            self.overrides.insert(msg.from_pid, canonical);
+        }
+
+        match self.resolve(msg.from_pid, &TimeOverride::default()) {
+            Ok((zone, locale, use_24h)) => {
+                let effective = TimeSettings {
+                    time_format_24h: use_24h,
+                    timezone: zone.name,
+                    locale: String::from(locale.tag),
+                };
+                self.send_settings_response(msg.from_pid, &msg.cap_slots, &effective, tag)
+            }
+            // Only the system settings can be invalid here
+            Err(e) => self.send_tagged_error(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Drop the overrides of processes that have exited if the table is
+    /// full. Returns true if there is room for another.
+    fn make_room_for_override(&mut self) -> bool {
+        if self.overrides.len() < MAX_OVERRIDES {
+            return true;
+        }
+        let running: Vec<u32> = syscall::list_processes().iter().map(|p| p.pid).collect();
+        self.overrides.retain(|pid, _| running.contains(pid));
+        self.overrides.len() < MAX_OVERRIDES
+    }
+
+    /// Handle MSG_TIME_CONVERT
+    fn handle_convert(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = time_msg::MSG_TIME_CONVERT_RESPONSE;
+        let request: ConvertRequest = if msg.data.is_empty() {
+            ConvertRequest::default()
+        } else {
+            match serde_json::from_slice(&msg.data) {
+                Ok(r) => r,
+                Err(_) => {
+                    return self.send_tagged_error(
+                        msg.from_pid,
+                        &msg.cap_slots,
+                        tag,
+                        "Invalid convert request: JSON parse failed",
+                    );
+                }
+            }
+        };
+        let timestamp_ms = request.timestamp_ms.unwrap_or_else(syscall::get_wallclock);
+
+        match self.convert(msg.from_pid, &request, timestamp_ms) {
+            Ok(converted) => {
+                let json = serde_json::to_vec(&converted).unwrap_or_default();
+                self.send_json_response(msg.from_pid, &msg.cap_slots, &json, tag)
+            }
+            Err(e) => self.send_tagged_error(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_TIME_SET_ALARM
+    fn handle_set_alarm(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = time_msg::MSG_TIME_SET_ALARM_RESPONSE;
+        let request: SetAlarmRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_tagged_error(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid alarm request: JSON parse failed",
+                );
+            }
+        };
+        // Repeating alarms keep the caller's local time
+        let zone = match self.resolve(msg.from_pid, &TimeOverride::default()) {
+            Ok((zone, _, _)) => zone,
+            Err(e) => return self.send_tagged_error(msg.from_pid, &msg.cap_slots, tag, &e),
+        };
+        let owner = process_name(msg.from_pid);
+
+        match self
+            .alarms
+            .add(&owner, request, &zone, syscall::get_wallclock())
+        {
+            Ok(alarm) => {
+                syscall::debug(&format!(
+                    "TimeService: {} set alarm {} for {}",
+                    owner, alarm.id, alarm.fire_at_ms
+                ));
+                self.save_alarms();
+                let json = serde_json::to_vec(&alarm).unwrap_or_default();
+                self.send_json_response(msg.from_pid, &msg.cap_slots, &json, tag)
+            }
+            Err(e) => self.send_tagged_error(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_TIME_CANCEL_ALARM
+    fn handle_cancel_alarm(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = time_msg::MSG_TIME_CANCEL_ALARM_RESPONSE;
+        let request: CancelAlarmRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_tagged_error(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid cancel request: JSON parse failed",
+                );
+            }
+        };
+
+        match self.alarms.cancel(&process_name(msg.from_pid), request.id) {
+            Ok(alarm) => {
+                self.save_alarms();
+                let json = serde_json::to_vec(&alarm).unwrap_or_default();
+                self.send_json_response(msg.from_pid, &msg.cap_slots, &json, tag)
+            }
+            Err(e) => self.send_tagged_error(msg.from_pid, &msg.cap_slots, tag, &e),
+        }
+    }
+
+    /// Handle MSG_TIME_LIST_ALARMS
+    fn handle_list_alarms(&mut self, msg: &Message) -> Result<(), AppError> {
+        let list = AlarmList {
+            alarms: self.alarms.list(&process_name(msg.from_pid)),
+        };
+        let json = serde_json::to_vec(&list).unwrap_or_default();
+        self.send_json_response(
+            msg.from_pid,
+            &msg.cap_slots,
+            &json,
+            time_msg::MSG_TIME_LIST_ALARMS_RESPONSE,
+        )
+    }
+
+    /// Fire the alarms due at `now_ms`: show each as a notification and
+    /// send it to every running process of its owner.
+    fn fire_due_alarms(&mut self, now_ms: u64) {
+        if !self.alarms_loaded {
+            return;
+        }
+        let fired = self.alarms.take_due(now_ms);
+        if fired.is_empty() {
+            return;
+        }
+        let processes = syscall::list_processes();
+        for alarm in &fired {
+            syscall::debug(&format!(
+                "TimeService: Alarm {} of {} fired",
+                alarm.id, alarm.owner
+            ));
+            self.notify(alarm);
+            let json = serde_json::to_vec(alarm).unwrap_or_default();
+            for process in processes.iter().filter(|p| p.name == alarm.owner) {
+                let _ = self.send_json_response(process.pid, &[], &json, time_msg::MSG_TIME_ALARM);
+            }
+        }
+        self.save_alarms();
+    }
+
+    /// Show `alarm` as a desktop notification, via the supervisor.
+    fn notify(&self, alarm: &Alarm) {
+        let request = ConvertRequest {
+            settings: TimeOverride {
+                timezone: Some(alarm.timezone.clone()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // PID 0 (the supervisor) has no override: system locale and clock
+        let time = self
+            .convert(0, &request, alarm.fire_at_ms)
+            .map(|t| t.time)
+            .unwrap_or_default();
+        let notification = Notification {
+            title: if alarm.label.is_empty() {
+                String::from("Alarm")
+            } else {
+                alarm.label.clone()
+            },
+            body: time,
+            source: alarm.owner.clone(),
+            tag: format!("alarm-{}", alarm.id),
+        };
+        let json = serde_json::to_vec(&notification).unwrap_or_default();
+        let hex: String = json.iter().map(|b| format!("{:02x}", b)).collect();
+        syscall::debug(&format!("{}{}", zos_ipc::debug::NOTIFY_SHOW, hex));
+    }
+
+    /// Start a write of the alarm store. Skipped until the store has
+    /// loaded, as it would replace the stored alarms.
+    fn save_alarms(&mut self) {
+        if !self.alarms_loaded || !self.check_pending_limit() {
+            return;
+        }
+        let value = self.alarms.to_json();
+        if let Err(e) = self.start_vfs_write(ALARMS_PATH, &value, PendingOp::SaveAlarms) {
+            syscall::debug(&format!("TimeService: Failed to save alarms: {}", e));
+        }
+    }
+
+    /// Resolve the zone, locale and clock for `pid`: `request`, then the
+    /// process's override, then the system settings. A clock that isn't set
+    /// explicitly follows the locale of the layer that chose it.
+    fn resolve(
+        &self,
+        pid: u32,
+        request: &TimeOverride,
+    ) -> Result<(TimeZone, &'static Locale, bool), String> {
+        let process = self.overrides.get(&pid).cloned().unwrap_or_default();
+        let layers = [request, &process];
+
+        let zone_name = layers
+            .iter()
+            .find_map(|o| o.timezone.clone())
+            .unwrap_or_else(|| self.settings.timezone.clone());
+        let tag = layers
+            .iter()
+            .find_map(|o| o.locale.clone())
+            .unwrap_or_else(|| self.settings.locale.clone());
+        let zone = TimeZone::lookup(&zone_name)
+            .ok_or_else(|| format!("Unknown timezone: {}", zone_name))?;
+        let locale = Locale::lookup(&tag).ok_or_else(|| format!("Unknown locale: {}", tag))?;
+        let use_24h = layers
+            .iter()
+            .find_map(|o| {
+                o.time_format_24h
+                    .or(o.locale.as_ref().map(|_| locale.default_24h))
+            })
+            .unwrap_or(self.settings.time_format_24h);
+        Ok((zone, locale, use_24h))
+    }
+
+    /// Format `timestamp_ms` for `pid` as `request` asks.
+    fn convert(
+        &self,
+        pid: u32,
+        request: &ConvertRequest,
+        timestamp_ms: u64,
+    ) -> Result<ConvertedTime, String> {
+        let (zone, locale, use_24h) = self.resolve(pid, &request.settings)?;
+        let utc_ms = timestamp_ms as i64;
+        let offset = zone.offset_at(utc_ms);
+        let fields = DateTimeFields::from_local_ms(utc_ms + offset.minutes as i64 * 60_000);
+
+        Ok(ConvertedTime {
+            timestamp_ms,
+            timezone: zone.name,
+            locale: String::from(locale.tag),
+            offset_minutes: offset.minutes,
+            utc_offset: format_offset(offset.minutes),
+            abbreviation: offset.abbreviation,
+            dst: offset.dst,
+            date: locale.format_date(&fields),
+            long_date: locale.format_long_date(&fields),
+            time: locale.format_time(&fields, use_24h, request.seconds),
+            fields,
+        })
+    }
+
     // =========================================================================
     // VFS Response Handlers
     // =========================================================================
@@ -466,6 +913,22 @@ impl TimeService {
                 )
             }
 
+            PendingOp::LoadAlarms => {
+                let armed_while_loading = !self.alarms.is_empty();
+                match result.ok().and_then(|data| AlarmTable::from_json(&data)) {
+                    Some(stored) => {
+                        syscall::debug(&format!("TimeService: Loaded {} alarms", stored.len()));
+                        self.alarms.merge(stored);
+                    }
+                    None => syscall::debug("TimeService: No stored alarms found"),
+                }
+                self.alarms_loaded = true;
+                if armed_while_loading {
+                    self.save_alarms();
+                }
+                Ok(())
+            }
+
             PendingOp::InitialLoad => {
                 match result {
                     Ok(data) => {
@@ -545,6 +1008,16 @@ impl TimeService {
                 }
             }
 
+            PendingOp::SaveAlarms => {
+                if let Err(e) = result {
+                    syscall::debug(&format!(
+                        "TimeService: Failed to write {}: {}",
+                        ALARMS_PATH, e
+                    ));
+                }
+                Ok(())
+            }
+
             _ => {
                 syscall::debug("TimeService: Unexpected pending operation for write response");
                 Ok(())
@@ -564,15 +1037,24 @@ impl TimeService {
         settings: &TimeSettings,
         response_tag: u32,
     ) -> Result<(), AppError> {
-        let json = settings.to_json();
+        self.send_json_response(to_pid, cap_slots, &settings.to_json(), response_tag)
+    }
 
+    /// Send a JSON response via reply cap, falling back to the debug channel
+    fn send_json_response(
+        &self,
+        to_pid: u32,
+        cap_slots: &[u32],
+        json: &[u8],
+        response_tag: u32,
+    ) -> Result<(), AppError> {
         // Try to send via transferred reply capability first
         if let Some(&reply_slot) = cap_slots.first() {
             syscall::debug(&format!(
                 "TimeService: Sending settings response via reply cap slot {} (tag 0x{:x})",
                 reply_slot, response_tag
             ));
-            match syscall::send(reply_slot, response_tag, &json) {
+            match syscall::send(reply_slot, response_tag, json) {
                 Ok(()) => {
                     syscall::debug("TimeService: Response sent via reply cap");
                     return Ok(());
@@ -595,6 +1077,18 @@ impl TimeService {
         Ok(())
     }
 
+    /// Send `{"error": ...}` tagged with `response_tag`
+    fn send_tagged_error(
+        &self,
+        to_pid: u32,
+        cap_slots: &[u32],
+        response_tag: u32,
+        error: &str,
+    ) -> Result<(), AppError> {
+        let json = format!(r#"{{"error":"{}"}}"#, error.replace('"', "\\\""));
+        self.send_json_response(to_pid, cap_slots, json.as_bytes(), response_tag)
+    }
+
     /// Send error response
     fn send_error_response(
         &self,
@@ -652,14 +1146,18 @@ impl ZeroApp for TimeService {
         self.registered = true;
 
         syscall::debug("TimeService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
 
         // Load settings via VFS on startup (Invariant 31 compliant)
         let _ = self.start_vfs_read(TimeSettings::storage_path(), PendingOp::InitialLoad);
+        // Then re-arm alarms
+        let _ = self.start_vfs_read(ALARMS_PATH, PendingOp::LoadAlarms);
 
         Ok(())
     }
 
-    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+    fn update(&mut self, ctx: &AppContext) -> ControlFlow {
+        self.fire_due_alarms(ctx.wallclock_ms);
         ControlFlow::Yield
     }
 
@@ -677,6 +1175,11 @@ impl ZeroApp for TimeService {
             // Time service protocol
             time_msg::MSG_GET_TIME_SETTINGS => self.handle_get_time_settings(ctx, &msg),
             time_msg::MSG_SET_TIME_SETTINGS => self.handle_set_time_settings(ctx, &msg),
+            time_msg::MSG_TIME_SET_OVERRIDE => self.handle_set_override(&msg),
+            time_msg::MSG_TIME_CONVERT => self.handle_convert(&msg),
+            time_msg::MSG_TIME_SET_ALARM => self.handle_set_alarm(&msg),
+            time_msg::MSG_TIME_CANCEL_ALARM => self.handle_cancel_alarm(&msg),
+            time_msg::MSG_TIME_LIST_ALARMS => self.handle_list_alarms(&msg),
             
             _ => {
                 syscall::debug(&format!(
@@ -717,11 +1220,13 @@ mod tests {
         let settings = TimeSettings {
             time_format_24h: true,
             timezone: String::from("America/New_York"),
+            locale: String::from("en-GB"),
         };
         let json = settings.to_json();
         let parsed = TimeSettings::from_json(&json).expect("should parse");
         assert_eq!(parsed.time_format_24h, true);
         assert_eq!(parsed.timezone, "America/New_York");
+        assert_eq!(parsed.locale, "en-GB");
     }
 
     #[test]
@@ -735,6 +1240,7 @@ mod tests {
         let result = TimeSettings::from_json(b"{}").expect("should parse empty object");
         assert!(!result.time_format_24h); // default
         assert_eq!(result.timezone, "UTC"); // default
+        assert_eq!(result.locale, "en-US"); // default
     }
 
     // -------------------------------------------------------------------------
@@ -818,4 +1324,127 @@ mod tests {
         let next = service.alloc_request_id();
         assert_eq!(next, 1); // Skipped 0
     }
+
+    // -------------------------------------------------------------------------
+    // Override and conversion tests
+    // -------------------------------------------------------------------------
+
+    /// 2024-07-04 16:30:00 UTC
+    const JULY_4: u64 = 1_720_110_600_000;
+
+    fn override_json(json: &str) -> TimeOverride {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_convert_uses_system_settings() {
+        let mut service = TimeService::default();
+        service.settings.timezone = String::from("America/New_York");
+        let converted = service
+            .convert(7, &ConvertRequest::default(), JULY_4)
+            .unwrap();
+        assert_eq!(converted.abbreviation, "EDT");
+        assert_eq!(converted.utc_offset, "-04:00");
+        assert_eq!(converted.date, "07/04/2024");
+        assert_eq!(converted.time, "12:30 PM");
+        assert_eq!(converted.long_date, "Thursday, July 4, 2024");
+    }
+
+    #[test]
+    fn test_override_is_per_process() {
+        let mut service = TimeService::default();
+        service.overrides.insert(
+            7,
+            canonicalize(override_json(r#"{"timezone":"asia/tokyo","locale":"ja"}"#)).unwrap(),
+        );
+
+        let world_clock = service
+            .convert(7, &ConvertRequest::default(), JULY_4)
+            .unwrap();
+        assert_eq!(world_clock.timezone, "Asia/Tokyo");
+        assert_eq!(world_clock.date, "2024/07/05");
+        // ja-JP defaults to a 24-hour clock
+        assert_eq!(world_clock.time, "01:30");
+
+        let other = service
+            .convert(8, &ConvertRequest::default(), JULY_4)
+            .unwrap();
+        assert_eq!(other.timezone, "UTC");
+        assert_eq!(other.time, "4:30 PM");
+        assert_eq!(service.settings.timezone, "UTC");
+    }
+
+    #[test]
+    fn test_request_beats_override() {
+        let mut service = TimeService::default();
+        service
+            .overrides
+            .insert(7, override_json(r#"{"timezone":"Asia/Tokyo","time_format_24h":false}"#));
+        let request: ConvertRequest =
+            serde_json::from_str(r#"{"timezone":"UTC+05:30","seconds":true}"#).unwrap();
+        let converted = service.convert(7, &request, JULY_4).unwrap();
+        assert_eq!(converted.offset_minutes, 330);
+        assert_eq!(converted.time, "10:00:00 PM");
+    }
+
+    #[test]
+    fn test_invalid_override_rejected() {
+        assert!(canonicalize(override_json(r#"{"timezone":"Mars/Base"}"#)).is_err());
+        assert!(canonicalize(override_json(r#"{"locale":"tlh"}"#)).is_err());
+
+        let mut service = TimeService::default();
+        let msg = crate::test_utils::mock_message(
+            time_msg::MSG_TIME_SET_OVERRIDE,
+            7,
+            br#"{"timezone":"Mars/Base"}"#.to_vec(),
+        );
+        service.handle_set_override(&msg).unwrap();
+        assert!(service.overrides.is_empty());
+    }
+
+    // -------------------------------------------------------------------------
+    // Alarm tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_alarms_belong_to_their_app() {
+        let mut service = TimeService::default();
+        let set = crate::test_utils::mock_message(
+            time_msg::MSG_TIME_SET_ALARM,
+            7,
+            br#"{"in_ms":60000,"label":"Tea"}"#.to_vec(),
+        );
+        service.handle_set_alarm(&set).unwrap();
+        let owner = process_name(7);
+        let alarm = service.alarms.list(&owner)[0].clone();
+        assert_eq!(alarm.fire_at_ms, syscall::get_wallclock() + 60_000);
+
+        let cancel = crate::test_utils::mock_message(
+            time_msg::MSG_TIME_CANCEL_ALARM,
+            8,
+            format!(r#"{{"id":{}}}"#, alarm.id).into_bytes(),
+        );
+        service.handle_cancel_alarm(&cancel).unwrap();
+        assert_eq!(service.alarms.len(), 1);
+    }
+
+    #[test]
+    fn test_alarms_fire_once_loaded() {
+        let mut service = TimeService::default();
+        let set = crate::test_utils::mock_message(
+            time_msg::MSG_TIME_SET_ALARM,
+            7,
+            br#"{"in_ms":1000}"#.to_vec(),
+        );
+        service.handle_set_alarm(&set).unwrap();
+        let due = syscall::get_wallclock() + 1000;
+
+        // Not before the store has loaded: it may hold more that are due
+        service.fire_due_alarms(due);
+        assert_eq!(service.alarms.len(), 1);
+
+        service.alarms_loaded = true;
+        service.fire_due_alarms(due);
+        assert!(service.alarms.is_empty());
+    }
 }
@@ -0,0 +1,362 @@
+//! Time zone rules
+//!
+//! A compact table of common IANA zones with their current standard offset
+//! and daylight saving rule, so apps can ask the TimeService for local time
+//! instead of bundling a tz database. Only today's rules are known: past
+//! changes to a zone's offset or DST dates are not reflected.
+//!
+//! Fixed offsets are also accepted as `UTC+05:30`, `UTC-8` or `GMT+1`.
+
+use alloc::format;
+use alloc::string::String;
+
+/// Milliseconds per minute.
+const MS_PER_MINUTE: i64 = 60_000;
+
+/// Milliseconds per day.
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// Largest fixed offset accepted, in minutes.
+const MAX_OFFSET_MINUTES: i32 = 14 * 60;
+
+/// When a zone observes daylight saving time (always one hour).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DstRule {
+    /// No daylight saving
+    None,
+    /// Second Sunday in March 02:00 to first Sunday in November 02:00 local
+    Us,
+    /// Last Sunday in March to last Sunday in October, 01:00 UTC
+    Eu,
+    /// First Sunday in October 02:00 to first Sunday in April 03:00 local
+    Au,
+    /// Last Sunday in September 02:00 to first Sunday in April 03:00 local
+    Nz,
+}
+
+/// One zone: name, standard offset in minutes, standard and daylight
+/// abbreviations, rule.
+type ZoneRow = (&'static str, i32, &'static str, &'static str, DstRule);
+
+const ZONES: &[ZoneRow] = &[
+    ("UTC", 0, "UTC", "UTC", DstRule::None),
+    ("Etc/UTC", 0, "UTC", "UTC", DstRule::None),
+    ("GMT", 0, "GMT", "GMT", DstRule::None),
+    // Americas
+    ("America/New_York", -300, "EST", "EDT", DstRule::Us),
+    ("America/Toronto", -300, "EST", "EDT", DstRule::Us),
+    ("America/Chicago", -360, "CST", "CDT", DstRule::Us),
+    ("America/Mexico_City", -360, "CST", "CST", DstRule::None),
+    ("America/Denver", -420, "MST", "MDT", DstRule::Us),
+    ("America/Phoenix", -420, "MST", "MST", DstRule::None),
+    ("America/Los_Angeles", -480, "PST", "PDT", DstRule::Us),
+    ("America/Vancouver", -480, "PST", "PDT", DstRule::Us),
+    ("America/Anchorage", -540, "AKST", "AKDT", DstRule::Us),
+    ("Pacific/Honolulu", -600, "HST", "HST", DstRule::None),
+    ("America/Sao_Paulo", -180, "-03", "-03", DstRule::None),
+    (
+        "America/Argentina/Buenos_Aires",
+        -180,
+        "-03",
+        "-03",
+        DstRule::None,
+    ),
+    // Europe and Africa
+    ("Europe/London", 0, "GMT", "BST", DstRule::Eu),
+    ("Europe/Dublin", 0, "GMT", "IST", DstRule::Eu),
+    ("Europe/Lisbon", 0, "WET", "WEST", DstRule::Eu),
+    ("Europe/Paris", 60, "CET", "CEST", DstRule::Eu),
+    ("Europe/Berlin", 60, "CET", "CEST", DstRule::Eu),
+    ("Europe/Madrid", 60, "CET", "CEST", DstRule::Eu),
+    ("Europe/Rome", 60, "CET", "CEST", DstRule::Eu),
+    ("Europe/Amsterdam", 60, "CET", "CEST", DstRule::Eu),
+    ("Europe/Zurich", 60, "CET", "CEST", DstRule::Eu),
+    ("Europe/Stockholm", 60, "CET", "CEST", DstRule::Eu),
+    ("Europe/Warsaw", 60, "CET", "CEST", DstRule::Eu),
+    ("Europe/Athens", 120, "EET", "EEST", DstRule::Eu),
+    ("Europe/Helsinki", 120, "EET", "EEST", DstRule::Eu),
+    ("Europe/Kyiv", 120, "EET", "EEST", DstRule::Eu),
+    ("Europe/Istanbul", 180, "+03", "+03", DstRule::None),
+    ("Europe/Moscow", 180, "MSK", "MSK", DstRule::None),
+    ("Africa/Lagos", 60, "WAT", "WAT", DstRule::None),
+    ("Africa/Johannesburg", 120, "SAST", "SAST", DstRule::None),
+    ("Africa/Nairobi", 180, "EAT", "EAT", DstRule::None),
+    // Asia and Oceania
+    ("Asia/Dubai", 240, "+04", "+04", DstRule::None),
+    ("Asia/Karachi", 300, "PKT", "PKT", DstRule::None),
+    ("Asia/Kolkata", 330, "IST", "IST", DstRule::None),
+    ("Asia/Dhaka", 360, "+06", "+06", DstRule::None),
+    ("Asia/Bangkok", 420, "+07", "+07", DstRule::None),
+    ("Asia/Jakarta", 420, "WIB", "WIB", DstRule::None),
+    ("Asia/Singapore", 480, "+08", "+08", DstRule::None),
+    ("Asia/Hong_Kong", 480, "HKT", "HKT", DstRule::None),
+    ("Asia/Shanghai", 480, "CST", "CST", DstRule::None),
+    ("Asia/Taipei", 480, "CST", "CST", DstRule::None),
+    ("Asia/Seoul", 540, "KST", "KST", DstRule::None),
+    ("Asia/Tokyo", 540, "JST", "JST", DstRule::None),
+    ("Australia/Perth", 480, "AWST", "AWST", DstRule::None),
+    ("Australia/Darwin", 570, "ACST", "ACST", DstRule::None),
+    ("Australia/Adelaide", 570, "ACST", "ACDT", DstRule::Au),
+    ("Australia/Brisbane", 600, "AEST", "AEST", DstRule::None),
+    ("Australia/Sydney", 600, "AEST", "AEDT", DstRule::Au),
+    ("Australia/Melbourne", 600, "AEST", "AEDT", DstRule::Au),
+    ("Pacific/Auckland", 720, "NZST", "NZDT", DstRule::Nz),
+];
+
+/// A resolved time zone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeZone {
+    /// Name as requested (canonical for table zones)
+    pub name: String,
+    std_offset: i32,
+    std_abbr: String,
+    dst_abbr: String,
+    rule: DstRule,
+}
+
+/// UTC offset in effect at an instant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZoneOffset {
+    /// Minutes east of UTC
+    pub minutes: i32,
+    /// Whether daylight saving time is in effect
+    pub dst: bool,
+    /// Abbreviation, e.g. "EDT" or "+05:30"
+    pub abbreviation: String,
+}
+
+impl TimeZone {
+    /// UTC, for when a stored zone name no longer resolves.
+    pub fn utc() -> Self {
+        Self {
+            name: "UTC".into(),
+            std_offset: 0,
+            std_abbr: "UTC".into(),
+            dst_abbr: "UTC".into(),
+            rule: DstRule::None,
+        }
+    }
+
+    /// Look up `name`: a table zone (case-insensitive) or a fixed offset.
+    pub fn lookup(name: &str) -> Option<Self> {
+        if let Some(&(zone, offset, std, dst, rule)) =
+            ZONES.iter().find(|z| z.0.eq_ignore_ascii_case(name))
+        {
+            return Some(Self {
+                name: zone.into(),
+                std_offset: offset,
+                std_abbr: std.into(),
+                dst_abbr: dst.into(),
+                rule,
+            });
+        }
+        let offset = parse_fixed_offset(name)?;
+        let abbr = format_offset(offset);
+        Some(Self {
+            name: name.into(),
+            std_offset: offset,
+            std_abbr: abbr.clone(),
+            dst_abbr: abbr,
+            rule: DstRule::None,
+        })
+    }
+
+    /// Offset in effect at `utc_ms` (ms since the Unix epoch).
+    pub fn offset_at(&self, utc_ms: i64) -> ZoneOffset {
+        let dst = self.is_dst(utc_ms);
+        ZoneOffset {
+            minutes: self.std_offset + if dst { 60 } else { 0 },
+            dst,
+            abbreviation: if dst {
+                self.dst_abbr.clone()
+            } else {
+                self.std_abbr.clone()
+            },
+        }
+    }
+
+    fn is_dst(&self, utc_ms: i64) -> bool {
+        let std_ms = self.std_offset as i64 * MS_PER_MINUTE;
+        let (year, _, _) = civil_from_days((utc_ms + std_ms).div_euclid(MS_PER_DAY));
+        // Local wall-clock time of day -> UTC, for standard or daylight time
+        let local = |days: i64, hour: i64, dst: bool| {
+            days * MS_PER_DAY + hour * 3_600_000 - std_ms - if dst { 3_600_000 } else { 0 }
+        };
+        let utc = |days: i64, hour: i64| days * MS_PER_DAY + hour * 3_600_000;
+
+        match self.rule {
+            DstRule::None => false,
+            DstRule::Us => {
+                let start = local(nth_sunday(year, 3, 2), 2, false);
+                let end = local(nth_sunday(year, 11, 1), 2, true);
+                utc_ms >= start && utc_ms < end
+            }
+            DstRule::Eu => {
+                let start = utc(last_sunday(year, 3), 1);
+                let end = utc(last_sunday(year, 10), 1);
+                utc_ms >= start && utc_ms < end
+            }
+            DstRule::Au => {
+                let end = local(nth_sunday(year, 4, 1), 3, true);
+                let start = local(nth_sunday(year, 10, 1), 2, false);
+                utc_ms < end || utc_ms >= start
+            }
+            DstRule::Nz => {
+                let end = local(nth_sunday(year, 4, 1), 3, true);
+                let start = local(last_sunday(year, 9), 2, false);
+                utc_ms < end || utc_ms >= start
+            }
+        }
+    }
+}
+
+/// Parse `UTC+05:30`, `UTC-8`, `GMT+1` and the like into minutes.
+fn parse_fixed_offset(name: &str) -> Option<i32> {
+    let upper = name.to_ascii_uppercase();
+    let rest = upper
+        .strip_prefix("UTC")
+        .or_else(|| upper.strip_prefix("GMT"))?;
+    let (sign, rest) = match rest.as_bytes().first()? {
+        b'+' => (1, &rest[1..]),
+        b'-' => (-1, &rest[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h, m.parse::<i32>().ok()?),
+        Some(_) => return None,
+        None => (rest, 0),
+    };
+    if hours.is_empty() || hours.len() > 2 || minutes >= 60 {
+        return None;
+    }
+    let total = hours.parse::<i32>().ok()? * 60 + minutes;
+    (total <= MAX_OFFSET_MINUTES).then_some(sign * total)
+}
+
+/// `+05:30` style rendering of an offset in minutes.
+pub fn format_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.abs();
+    format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
+
+// =============================================================================
+// Calendar arithmetic
+// =============================================================================
+
+/// (year, month 1-12, day 1-31) of `days` since 1970-01-01.
+///
+/// Howard Hinnant's `civil_from_days`, valid for the proleptic Gregorian
+/// calendar.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Days since 1970-01-01 of a date; the inverse of [`civil_from_days`].
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Day of the week of `days` since 1970-01-01, 0 = Sunday.
+pub fn weekday(days: i64) -> u32 {
+    // 1970-01-01 was a Thursday
+    (days + 4).rem_euclid(7) as u32
+}
+
+/// Days since the epoch of the `n`th Sunday of `month`.
+fn nth_sunday(year: i64, month: u32, n: u32) -> i64 {
+    let first = days_from_civil(year, month, 1);
+    let to_sunday = (7 - weekday(first)) % 7;
+    first + to_sunday as i64 + 7 * (n as i64 - 1)
+}
+
+/// Days since the epoch of the last Sunday of `month`.
+fn last_sunday(year: i64, month: u32) -> i64 {
+    let next = if month == 12 {
+        days_from_civil(year + 1, 1, 1)
+    } else {
+        days_from_civil(year, month + 1, 1)
+    };
+    let last = next - 1;
+    last - weekday(last) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ms since the epoch of a UTC date and time.
+    fn utc(year: i64, month: u32, day: u32, hour: i64, minute: i64) -> i64 {
+        days_from_civil(year, month, day) * MS_PER_DAY + hour * 3_600_000 + minute * 60_000
+    }
+
+    #[test]
+    fn test_calendar_round_trip() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        // 2024-03-10 was a Sunday
+        assert_eq!(weekday(days_from_civil(2024, 3, 10)), 0);
+        assert_eq!(nth_sunday(2024, 3, 2), days_from_civil(2024, 3, 10));
+        assert_eq!(last_sunday(2024, 10), days_from_civil(2024, 10, 27));
+    }
+
+    #[test]
+    fn test_us_transitions() {
+        let ny = TimeZone::lookup("america/new_york").unwrap();
+        assert_eq!(ny.name, "America/New_York");
+        // 2024-03-10 02:00 EST = 07:00 UTC
+        assert_eq!(ny.offset_at(utc(2024, 3, 10, 6, 59)).abbreviation, "EST");
+        assert_eq!(ny.offset_at(utc(2024, 3, 10, 7, 0)).minutes, -240);
+        // 2024-11-03 02:00 EDT = 06:00 UTC
+        assert!(ny.offset_at(utc(2024, 11, 3, 5, 59)).dst);
+        assert!(!ny.offset_at(utc(2024, 11, 3, 6, 0)).dst);
+    }
+
+    #[test]
+    fn test_eu_and_southern_transitions() {
+        let london = TimeZone::lookup("Europe/London").unwrap();
+        assert_eq!(london.offset_at(utc(2024, 3, 31, 0, 59)).minutes, 0);
+        assert_eq!(london.offset_at(utc(2024, 3, 31, 1, 0)).abbreviation, "BST");
+        assert_eq!(london.offset_at(utc(2024, 10, 27, 1, 0)).minutes, 0);
+
+        let sydney = TimeZone::lookup("Australia/Sydney").unwrap();
+        assert!(sydney.offset_at(utc(2024, 1, 15, 0, 0)).dst);
+        assert!(!sydney.offset_at(utc(2024, 7, 1, 0, 0)).dst);
+        // 2024-10-06 02:00 AEST = 2024-10-05 16:00 UTC
+        assert!(!sydney.offset_at(utc(2024, 10, 5, 15, 59)).dst);
+        assert_eq!(sydney.offset_at(utc(2024, 10, 5, 16, 0)).minutes, 660);
+    }
+
+    #[test]
+    fn test_fixed_offsets() {
+        let india = TimeZone::lookup("UTC+05:30").unwrap();
+        let offset = india.offset_at(0);
+        assert_eq!(offset.minutes, 330);
+        assert_eq!(offset.abbreviation, "+05:30");
+        assert_eq!(
+            TimeZone::lookup("GMT-8").unwrap().offset_at(0).minutes,
+            -480
+        );
+
+        for bad in ["UTC+15", "UTC+5:3", "UTC5", "Mars/Olympus_Mons", "UTC+"] {
+            assert!(TimeZone::lookup(bad).is_none(), "{}", bad);
+        }
+        assert_eq!(TimeZone::lookup("UTC"), Some(TimeZone::utc()));
+    }
+}
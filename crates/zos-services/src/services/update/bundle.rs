@@ -0,0 +1,323 @@
+//! Signed system bundle manifests
+//!
+//! A bundle manifest lists the files that make up a system image (kernel
+//! wasm, core service binaries) together with their SHA-256 digests, and is
+//! signed with the update signing key:
+//!
+//! ```json
+//! {
+//!   "version": "0.2.0",
+//!   "files": [
+//!     {"name": "kernel.wasm", "url": "https://.../kernel.wasm", "sha256": "…", "size": 123}
+//!   ],
+//!   "signature": "<128 hex chars>"
+//! }
+//! ```
+//!
+//! The signature covers [`BundleManifest::signing_payload`], which omits the
+//! download URLs so bundles can be mirrored without re-signing.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Domain separator prepended to the signed payload.
+const SIGNING_DOMAIN: &str = "zos-update-bundle-v1";
+
+/// Upper bound on files per bundle (DoS protection per Rule 11).
+pub const MAX_BUNDLE_FILES: usize = 32;
+
+/// VFS path of the hex-encoded Ed25519 update signing key.
+pub const SIGNING_KEY_PATH: &str = "/system/update/signing_key";
+
+/// File name of the manifest copy stored alongside a staged slot.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// One file in a system bundle.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleFile {
+    /// File name inside the slot (no path separators)
+    pub name: String,
+    /// Download URL
+    pub url: String,
+    /// Lowercase hex SHA-256 of the file contents
+    pub sha256: String,
+    /// File size in bytes
+    pub size: u64,
+}
+
+/// A signed system bundle manifest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// Bundle version
+    pub version: String,
+    /// Files in the bundle
+    pub files: Vec<BundleFile>,
+    /// Hex-encoded Ed25519 signature over `signing_payload()`
+    pub signature: String,
+}
+
+/// Errors from bundle validation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BundleError {
+    /// Manifest JSON could not be parsed
+    Malformed(String),
+    /// Manifest structure is invalid (empty, too many files, bad names)
+    Invalid(String),
+    /// Signature or signing key is malformed or does not verify
+    BadSignature,
+    /// A downloaded file does not match the manifest
+    DigestMismatch(String),
+}
+
+impl BundleError {
+    /// Human-readable error message.
+    pub fn message(&self) -> String {
+        match self {
+            BundleError::Malformed(e) => format!("Malformed bundle manifest: {}", e),
+            BundleError::Invalid(e) => format!("Invalid bundle manifest: {}", e),
+            BundleError::BadSignature => String::from("Bundle signature verification failed"),
+            BundleError::DigestMismatch(name) => {
+                format!("Downloaded file {} does not match manifest digest", name)
+            }
+        }
+    }
+}
+
+impl BundleManifest {
+    /// Parse and structurally validate a manifest. Does not check the signature.
+    pub fn from_json(data: &[u8]) -> Result<Self, BundleError> {
+        let manifest: Self =
+            serde_json::from_slice(data).map_err(|e| BundleError::Malformed(format!("{}", e)))?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Serialize to JSON bytes.
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    fn validate(&self) -> Result<(), BundleError> {
+        if self.version.is_empty() || self.version.contains('\n') {
+            return Err(BundleError::Invalid(String::from("bad version")));
+        }
+        if self.files.is_empty() {
+            return Err(BundleError::Invalid(String::from("no files")));
+        }
+        if self.files.len() > MAX_BUNDLE_FILES {
+            return Err(BundleError::Invalid(format!(
+                "{} files exceeds limit of {}",
+                self.files.len(),
+                MAX_BUNDLE_FILES
+            )));
+        }
+        for (i, file) in self.files.iter().enumerate() {
+            if !is_valid_file_name(&file.name) {
+                return Err(BundleError::Invalid(format!(
+                    "bad file name {:?}",
+                    file.name
+                )));
+            }
+            if self.files[..i].iter().any(|f| f.name == file.name) {
+                return Err(BundleError::Invalid(format!(
+                    "duplicate file {}",
+                    file.name
+                )));
+            }
+            if decode_hex(&file.sha256).map(|d| d.len()) != Some(32) {
+                return Err(BundleError::Invalid(format!(
+                    "bad digest for {}",
+                    file.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Bytes covered by the signature.
+    ///
+    /// Format: domain, version, then one `name\tsha256\tsize` line per file.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut payload = format!("{}\n{}\n", SIGNING_DOMAIN, self.version);
+        for file in &self.files {
+            payload.push_str(&format!(
+                "{}\t{}\t{}\n",
+                file.name,
+                file.sha256.to_ascii_lowercase(),
+                file.size
+            ));
+        }
+        payload.into_bytes()
+    }
+
+    /// Verify the manifest signature against the trusted signing key.
+    pub fn verify_signature(&self, public_key: &[u8; 32]) -> Result<(), BundleError> {
+        let key = VerifyingKey::from_bytes(public_key).map_err(|_| BundleError::BadSignature)?;
+        let sig_bytes: [u8; 64] = decode_hex(&self.signature)
+            .and_then(|b| b.try_into().ok())
+            .ok_or(BundleError::BadSignature)?;
+        let signature = Signature::from_bytes(&sig_bytes);
+        key.verify(&self.signing_payload(), &signature)
+            .map_err(|_| BundleError::BadSignature)
+    }
+
+    /// Check downloaded contents against the manifest entry.
+    pub fn verify_file(file: &BundleFile, contents: &[u8]) -> Result<(), BundleError> {
+        if contents.len() as u64 != file.size {
+            return Err(BundleError::DigestMismatch(file.name.clone()));
+        }
+        let digest = Sha256::digest(contents);
+        match decode_hex(&file.sha256) {
+            Some(expected) if expected.as_slice() == digest.as_slice() => Ok(()),
+            _ => Err(BundleError::DigestMismatch(file.name.clone())),
+        }
+    }
+}
+
+/// Parse the hex-encoded signing key stored at [`SIGNING_KEY_PATH`].
+pub fn parse_signing_key(data: &[u8]) -> Option<[u8; 32]> {
+    let text = core::str::from_utf8(data).ok()?;
+    decode_hex(text.trim())?.try_into().ok()
+}
+
+/// Bundle file names are flat: no separators, no dot-files, no manifest clash.
+fn is_valid_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with('.')
+        && name != MANIFEST_FILE_NAME
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    fn signed_manifest(contents: &[u8]) -> BundleManifest {
+        let mut manifest = BundleManifest {
+            version: String::from("0.2.0"),
+            files: alloc::vec![BundleFile {
+                name: String::from("kernel.wasm"),
+                url: String::from("https://updates.example/0.2.0/kernel.wasm"),
+                sha256: hex(&Sha256::digest(contents)),
+                size: contents.len() as u64,
+            }],
+            signature: String::new(),
+        };
+        let signature = signing_key().sign(&manifest.signing_payload());
+        manifest.signature = hex(&signature.to_bytes());
+        manifest
+    }
+
+    #[test]
+    fn test_valid_signature_verifies() {
+        let manifest = signed_manifest(b"kernel");
+        let public = signing_key().verifying_key().to_bytes();
+        assert_eq!(manifest.verify_signature(&public), Ok(()));
+    }
+
+    #[test]
+    fn test_tampered_manifest_fails_signature() {
+        let mut manifest = signed_manifest(b"kernel");
+        manifest.files[0].size += 1;
+        let public = signing_key().verifying_key().to_bytes();
+        assert_eq!(
+            manifest.verify_signature(&public),
+            Err(BundleError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_wrong_key_fails_signature() {
+        let manifest = signed_manifest(b"kernel");
+        let other = SigningKey::from_bytes(&[9u8; 32])
+            .verifying_key()
+            .to_bytes();
+        assert_eq!(
+            manifest.verify_signature(&other),
+            Err(BundleError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_url_not_covered_by_signature() {
+        let mut manifest = signed_manifest(b"kernel");
+        manifest.files[0].url = String::from("https://mirror.example/kernel.wasm");
+        let public = signing_key().verifying_key().to_bytes();
+        assert_eq!(manifest.verify_signature(&public), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_file_digest() {
+        let manifest = signed_manifest(b"kernel");
+        let file = &manifest.files[0];
+        assert_eq!(BundleManifest::verify_file(file, b"kernel"), Ok(()));
+        assert!(matches!(
+            BundleManifest::verify_file(file, b"kerneX"),
+            Err(BundleError::DigestMismatch(_))
+        ));
+        assert!(BundleManifest::verify_file(file, b"kernel!").is_err());
+    }
+
+    #[test]
+    fn test_from_json_round_trip() {
+        let manifest = signed_manifest(b"kernel");
+        let parsed = BundleManifest::from_json(&manifest.to_json()).unwrap();
+        assert_eq!(parsed, manifest);
+    }
+
+    #[test]
+    fn test_rejects_path_traversal_names() {
+        let mut manifest = signed_manifest(b"kernel");
+        manifest.files[0].name = String::from("../init.wasm");
+        assert!(matches!(
+            BundleManifest::from_json(&manifest.to_json()),
+            Err(BundleError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_duplicate_and_empty() {
+        let mut manifest = signed_manifest(b"kernel");
+        let dup = manifest.files[0].clone();
+        manifest.files.push(dup);
+        assert!(BundleManifest::from_json(&manifest.to_json()).is_err());
+
+        manifest.files.clear();
+        assert!(BundleManifest::from_json(&manifest.to_json()).is_err());
+    }
+
+    #[test]
+    fn test_parse_signing_key() {
+        let public = signing_key().verifying_key().to_bytes();
+        let stored = format!("{}\n", hex(&public));
+        assert_eq!(parse_signing_key(stored.as_bytes()), Some(public));
+        assert_eq!(parse_signing_key(b"abcd"), None);
+        assert_eq!(parse_signing_key(b"zz"), None);
+    }
+}
@@ -0,0 +1,1080 @@
+//! Update Service
+//!
+//! The UpdateService installs new system bundles (kernel wasm, core service
+//! binaries) using an A/B slot layout in VFS. It:
+//! - Downloads a signed bundle manifest and its files via network syscalls
+//! - Verifies the manifest signature and every file digest before staging
+//! - Writes the bundle into the inactive slot and records it as pending
+//! - Boots the pending slot on trial at the next boot
+//! - Commits the slot once boot health is confirmed, or rolls back
+//!
+//! # Safety Invariants
+//!
+//! **Success means:**
+//! - STAGE: All files verified AND written AND slot state persisted as pending
+//! - HEALTH_OK: Trial slot persisted as active
+//! - HEALTH_FAILED: Trial slot abandoned AND state persisted
+//!
+//! **Acceptable partial failure:**
+//! - Download or write fails mid-stage → previous slots untouched, error response
+//! - Service restarts during a trial boot → counts as another boot attempt
+//!
+//! **Forbidden:**
+//! - Marking a slot pending before every file passed digest verification
+//! - Staging a bundle whose manifest signature does not verify
+//! - Rewriting the active slot (only the inactive slot is ever written)
+//! - Allowing unauthorized processes to stage updates or report health
+//! - Unbounded pending operations (DoS vector)
+//!
+//! # Protocol
+//!
+//! - `MSG_UPDATE_STAGE (0x8200)`: Download and stage a bundle
+//! - `MSG_UPDATE_STATUS (0x8202)`: Query slot state
+//! - `MSG_UPDATE_HEALTH_OK (0x8204)`: Boot health check passed (sent by init)
+//! - `MSG_UPDATE_HEALTH_FAILED (0x8206)`: Boot health check failed
+//!
+//! # Boot Health
+//!
+//! Every boot calls [`SlotState::begin_boot`]. A pending slot gets
+//! [`MAX_TRIAL_BOOTS`] boots to be confirmed; init sends
+//! `MSG_UPDATE_HEALTH_OK` once all boot-critical services have registered.
+//! A trial that never gets confirmed (crash, hang, missing service) is rolled
+//! back on the following boot.
+//!
+//! # Storage Access
+//!
+//! Slot contents and state are written via VFS IPC (async pattern) per
+//! Invariant 31. Slot state lives at `/system/update/state.json`.
+
+extern crate alloc;
+
+pub mod bundle;
+pub mod slots;
+
+use crate::manifests::UPDATE_MANIFEST;
+use crate::response::JsonResponder;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, ControlFlow, Message, ZeroApp};
+use zos_network::{HttpRequest, HttpResponse, HttpSuccess, NetworkError};
+use zos_process::net;
+use zos_vfs::async_client;
+use zos_vfs::ipc::vfs_msg;
+
+pub use bundle::{BundleError, BundleFile, BundleManifest};
+pub use slots::{BootOutcome, Slot, SlotError, SlotState, MAX_TRIAL_BOOTS};
+
+// =============================================================================
+// IPC Message Tags
+// =============================================================================
+
+/// Message tags for update service - re-exported from zos-ipc.
+pub mod update_msg {
+    pub use zos_ipc::update::*;
+}
+
+// =============================================================================
+// Permission & Limit Constants
+// =============================================================================
+
+/// Maximum number of pending VFS/network operations (DoS protection per Rule 11)
+const MAX_PENDING_OPS: usize = 16;
+
+/// Maximum messages buffered while slot state is loading (Rule 11)
+const MAX_DEFERRED_MESSAGES: usize = 8;
+
+/// PIDs allowed to stage updates.
+/// - PID 0: Supervisor
+/// - PID 1: Init
+/// - PID 3: Desktop/Settings UI
+const TRUSTED_PIDS_FOR_STAGE: &[u32] = &[0, 1, 3];
+
+/// PIDs allowed to report boot health.
+/// - PID 0: Supervisor
+/// - PID 1: Init
+const TRUSTED_PIDS_FOR_HEALTH: &[u32] = &[0, 1];
+
+// =============================================================================
+// Request / Response Types
+// =============================================================================
+
+/// Payload of MSG_UPDATE_STAGE.
+#[derive(Clone, Debug, Deserialize)]
+struct StageRequest {
+    manifest_url: String,
+}
+
+/// Payload of MSG_UPDATE_HEALTH_FAILED.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct HealthFailedRequest {
+    #[serde(default)]
+    reason: String,
+}
+
+/// Slot state as reported to clients.
+#[derive(Clone, Debug, Serialize)]
+pub struct UpdateStatus {
+    /// Slot the current boot is running from
+    pub booted: Slot,
+    /// Whether a bundle is currently being downloaded
+    pub staging: bool,
+    /// Persistent slot state
+    #[serde(flatten)]
+    pub state: SlotState,
+}
+
+// =============================================================================
+// Pending Operations
+// =============================================================================
+
+/// In-progress download of a bundle into the inactive slot.
+struct StagingJob {
+    client_pid: u32,
+    cap_slots: Vec<u32>,
+    slot: Slot,
+    manifest: Option<BundleManifest>,
+    next_file: usize,
+}
+
+/// Pending VFS operations. VFS responses carry no request ID, so these are
+/// matched oldest-first by [`OpType`].
+#[derive(Clone, Debug)]
+enum PendingOp {
+    /// Initial load of the signing key
+    LoadSigningKey,
+    /// Initial load of slot state
+    LoadState,
+    /// Ensure /system/update exists before persisting boot state
+    PrepareStateDir,
+    /// Persist state after boot accounting
+    PersistBootState,
+    /// Persist state with any previously staged bundle cleared
+    ClearStaged,
+    /// Ensure the inactive slot directory exists
+    PrepareSlot,
+    /// Write a verified bundle file
+    WriteBundleFile { index: usize },
+    /// Write the manifest copy into the slot
+    WriteSlotManifest,
+    /// Persist the staged state
+    CommitStage { state: SlotState },
+    /// Persist a health report outcome
+    CommitHealth {
+        client_pid: u32,
+        cap_slots: Vec<u32>,
+        response_tag: u32,
+        state: SlotState,
+    },
+}
+
+/// Operation type for matching VFS responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OpType {
+    Read,
+    Write,
+    Mkdir,
+}
+
+/// Pending network fetches, keyed by syscall request ID.
+#[derive(Clone, Copy, Debug)]
+enum PendingFetch {
+    Manifest,
+    File { index: usize },
+}
+
+// =============================================================================
+// UpdateService Application
+// =============================================================================
+
+/// UpdateService - A/B system bundle staging and rollback
+pub struct UpdateService {
+    /// Whether we have registered with init
+    registered: bool,
+    /// Slot state (valid once `state_loaded`)
+    state: SlotState,
+    /// Whether slot state has been loaded and boot accounting done
+    state_loaded: bool,
+    /// Trusted Ed25519 update signing key
+    signing_key: Option<[u8; 32]>,
+    /// Bundle download in progress (at most one)
+    staging: Option<StagingJob>,
+    /// Pending VFS operations: request_id -> (operation, op_type)
+    pending_ops: BTreeMap<u32, (PendingOp, OpType)>,
+    /// Pending network fetches: syscall request_id -> fetch
+    pending_fetches: BTreeMap<u32, PendingFetch>,
+    /// Requests received before slot state finished loading
+    deferred: Vec<Message>,
+    /// Next request ID for VFS correlation
+    next_request_id: u32,
+}
+
+impl Default for UpdateService {
+    fn default() -> Self {
+        Self {
+            registered: false,
+            state: SlotState::default(),
+            state_loaded: false,
+            signing_key: None,
+            staging: None,
+            pending_ops: BTreeMap::new(),
+            pending_fetches: BTreeMap::new(),
+            deferred: Vec::new(),
+            next_request_id: 1,
+        }
+    }
+}
+
+impl UpdateService {
+    /// Allocate a new request ID for operation correlation.
+    fn alloc_request_id(&mut self) -> u32 {
+        let id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        if self.next_request_id == 0 {
+            self.next_request_id = 1;
+        }
+        id
+    }
+
+    /// Find and remove the oldest pending VFS operation of the given type.
+    fn take_pending_by_type(&mut self, op_type: OpType) -> Option<PendingOp> {
+        let request_id = self
+            .pending_ops
+            .iter()
+            .find(|(_, (_, t))| *t == op_type)
+            .map(|(id, _)| *id)?;
+        self.pending_ops.remove(&request_id).map(|(op, _)| op)
+    }
+
+    /// Check if caller may stage updates (Rule 4: fail-closed).
+    fn check_stage_permission(&self, from_pid: u32) -> bool {
+        let allowed = TRUSTED_PIDS_FOR_STAGE.contains(&from_pid);
+        if !allowed {
+            syscall::debug(&format!(
+                "UpdateService: SECURITY - STAGE denied for PID {}",
+                from_pid
+            ));
+        }
+        allowed
+    }
+
+    /// Check if caller may report boot health (Rule 4: fail-closed).
+    fn check_health_permission(&self, from_pid: u32) -> bool {
+        let allowed = TRUSTED_PIDS_FOR_HEALTH.contains(&from_pid);
+        if !allowed {
+            syscall::debug(&format!(
+                "UpdateService: SECURITY - health report denied for PID {}",
+                from_pid
+            ));
+        }
+        allowed
+    }
+
+    /// Check and enforce pending operation limits (DoS protection per Rule 11).
+    fn check_pending_limit(&self) -> bool {
+        let total = self.pending_ops.len() + self.pending_fetches.len();
+        if total >= MAX_PENDING_OPS {
+            syscall::debug(&format!(
+                "UpdateService: Pending operation limit reached ({}/{})",
+                total, MAX_PENDING_OPS
+            ));
+            false
+        } else {
+            true
+        }
+    }
+
+    fn status(&self) -> UpdateStatus {
+        UpdateStatus {
+            booted: self.state.booted_slot(),
+            staging: self.staging.is_some(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl UpdateService {
+    // =========================================================================
+    // VFS / network helpers (async, non-blocking)
+    // =========================================================================
+
+    fn start_vfs_read(&mut self, path: &str, op: PendingOp) -> Result<(), AppError> {
+        let request_id = self.alloc_request_id();
+        async_client::send_read_request(path)?;
+        self.pending_ops.insert(request_id, (op, OpType::Read));
+        Ok(())
+    }
+
+    fn start_vfs_write(&mut self, path: &str, value: &[u8], op: PendingOp) -> Result<(), AppError> {
+        let request_id = self.alloc_request_id();
+        syscall::debug(&format!(
+            "UpdateService: VFS write {} ({} bytes, req_id={})",
+            path,
+            value.len(),
+            request_id
+        ));
+        async_client::send_write_request(path, value)?;
+        self.pending_ops.insert(request_id, (op, OpType::Write));
+        Ok(())
+    }
+
+    fn start_vfs_mkdir(&mut self, path: &str, op: PendingOp) -> Result<(), AppError> {
+        let request_id = self.alloc_request_id();
+        async_client::send_mkdir_request(path, true)?;
+        self.pending_ops.insert(request_id, (op, OpType::Mkdir));
+        Ok(())
+    }
+
+    fn start_fetch(&mut self, url: &str, fetch: PendingFetch) -> Result<(), AppError> {
+        let request_json = serde_json::to_vec(&HttpRequest::get(url))
+            .map_err(|e| AppError::IpcError(format!("Request serialization failed: {}", e)))?;
+        match syscall::network_fetch_async(&request_json) {
+            Ok(request_id) => {
+                syscall::debug(&format!(
+                    "UpdateService: fetching {} (request_id={})",
+                    url, request_id
+                ));
+                self.pending_fetches.insert(request_id as u32, fetch);
+                Ok(())
+            }
+            Err(e) => Err(AppError::IpcError(format!(
+                "Network fetch failed for {}: {}",
+                url, e
+            ))),
+        }
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_UPDATE_STAGE
+    fn handle_stage(&mut self, msg: &Message) -> Result<(), AppError> {
+        let tag = update_msg::MSG_UPDATE_STAGE_RESPONSE;
+
+        if !self.check_stage_permission(msg.from_pid) {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: UPDATE_STAGE requires system privilege",
+            );
+        }
+        if self.staging.is_some() {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Service busy: an update is already being staged",
+            );
+        }
+        if !self.check_pending_limit() {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Service busy: pending operation limit reached",
+            );
+        }
+        if self.state.in_trial() {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                SlotError::TrialInProgress.message(),
+            );
+        }
+        if self.signing_key.is_none() {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                &format!("No update signing key at {}", bundle::SIGNING_KEY_PATH),
+            );
+        }
+
+        let request: StageRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(_) => {
+                return self.send_error_response(
+                    msg.from_pid,
+                    &msg.cap_slots,
+                    tag,
+                    "Invalid stage request: expected {\"manifest_url\": string}",
+                );
+            }
+        };
+
+        if let Err(e) = self.start_fetch(&request.manifest_url, PendingFetch::Manifest) {
+            return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e.to_string());
+        }
+
+        self.staging = Some(StagingJob {
+            client_pid: msg.from_pid,
+            cap_slots: msg.cap_slots.clone(),
+            slot: self.state.inactive(),
+            manifest: None,
+            next_file: 0,
+        });
+        Ok(())
+    }
+
+    /// Handle MSG_UPDATE_STATUS
+    fn handle_status(&mut self, msg: &Message) -> Result<(), AppError> {
+        let json = serde_json::to_vec(&self.status()).unwrap_or_default();
+        self.send_response(
+            msg.from_pid,
+            &msg.cap_slots,
+            update_msg::MSG_UPDATE_STATUS_RESPONSE,
+            &json,
+        )
+    }
+
+    /// Handle MSG_UPDATE_HEALTH_OK / MSG_UPDATE_HEALTH_FAILED
+    fn handle_health(&mut self, msg: &Message, healthy: bool) -> Result<(), AppError> {
+        let tag = if healthy {
+            update_msg::MSG_UPDATE_HEALTH_OK_RESPONSE
+        } else {
+            update_msg::MSG_UPDATE_HEALTH_FAILED_RESPONSE
+        };
+
+        if !self.check_health_permission(msg.from_pid) {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                tag,
+                "Permission denied: boot health reports require system privilege",
+            );
+        }
+
+        // Not on trial: nothing to commit, report current status
+        if !self.state.in_trial() {
+            return self.handle_status_with_tag(msg, tag);
+        }
+
+        let mut next = self.state.clone();
+        let result = if healthy {
+            next.confirm_healthy()
+        } else {
+            let request: HealthFailedRequest =
+                serde_json::from_slice(&msg.data).unwrap_or_default();
+            let reason = if request.reason.is_empty() {
+                "boot health check failed"
+            } else {
+                request.reason.as_str()
+            };
+            next.report_unhealthy(reason)
+        };
+        if let Err(e) = result {
+            return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, e.message());
+        }
+
+        syscall::debug(&format!(
+            "UpdateService: trial slot {} {}",
+            self.state.booted_slot().name(),
+            if healthy {
+                "confirmed healthy"
+            } else {
+                "abandoned"
+            }
+        ));
+
+        let value = next.to_json();
+        let op = PendingOp::CommitHealth {
+            client_pid: msg.from_pid,
+            cap_slots: msg.cap_slots.clone(),
+            response_tag: tag,
+            state: next,
+        };
+        if let Err(e) = self.start_vfs_write(slots::STATE_PATH, &value, op) {
+            return self.send_error_response(msg.from_pid, &msg.cap_slots, tag, &e.to_string());
+        }
+        Ok(())
+    }
+
+    fn handle_status_with_tag(&mut self, msg: &Message, tag: u32) -> Result<(), AppError> {
+        let json = serde_json::to_vec(&self.status()).unwrap_or_default();
+        self.send_response(msg.from_pid, &msg.cap_slots, tag, &json)
+    }
+
+    // =========================================================================
+    // Staging pipeline
+    // =========================================================================
+
+    /// Abort the staging job with an error response to the client.
+    fn fail_staging(&mut self, error: &str) -> Result<(), AppError> {
+        syscall::debug(&format!("UpdateService: staging failed: {}", error));
+        match self.staging.take() {
+            Some(job) => self.send_error_response(
+                job.client_pid,
+                &job.cap_slots,
+                update_msg::MSG_UPDATE_STAGE_RESPONSE,
+                error,
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Manifest downloaded: verify it, then clear any stale staged bundle.
+    fn on_manifest_fetched(&mut self, body: &[u8]) -> Result<(), AppError> {
+        let manifest = match BundleManifest::from_json(body) {
+            Ok(m) => m,
+            Err(e) => return self.fail_staging(&e.message()),
+        };
+        let Some(key) = self.signing_key else {
+            return self.fail_staging("Update signing key unavailable");
+        };
+        if let Err(e) = manifest.verify_signature(&key) {
+            return self.fail_staging(&e.message());
+        }
+
+        syscall::debug(&format!(
+            "UpdateService: manifest {} verified ({} files)",
+            manifest.version,
+            manifest.files.len()
+        ));
+
+        if let Some(job) = self.staging.as_mut() {
+            job.manifest = Some(manifest);
+        }
+
+        // The inactive slot is about to be overwritten: make sure a previously
+        // staged bundle there can no longer be selected for a trial boot.
+        let mut cleared = self.state.clone();
+        cleared.pending = None;
+        let value = cleared.to_json();
+        self.state = cleared;
+        if let Err(e) = self.start_vfs_write(slots::STATE_PATH, &value, PendingOp::ClearStaged) {
+            return self.fail_staging(&e.to_string());
+        }
+        Ok(())
+    }
+
+    /// Fetch the next bundle file, or finish once all are written.
+    fn stage_next_file(&mut self) -> Result<(), AppError> {
+        let (url, index, slot) = match self.staging.as_ref() {
+            Some(StagingJob {
+                manifest: Some(manifest),
+                next_file,
+                slot,
+                ..
+            }) => match manifest.files.get(*next_file) {
+                Some(file) => (file.url.clone(), *next_file, *slot),
+                None => {
+                    let json = manifest.to_json();
+                    let path = slot.file_path(bundle::MANIFEST_FILE_NAME);
+                    if let Err(e) = self.start_vfs_write(&path, &json, PendingOp::WriteSlotManifest)
+                    {
+                        return self.fail_staging(&e.to_string());
+                    }
+                    return Ok(());
+                }
+            },
+            _ => return Ok(()),
+        };
+
+        syscall::debug(&format!(
+            "UpdateService: downloading file {} into slot {}",
+            index,
+            slot.name()
+        ));
+        if let Err(e) = self.start_fetch(&url, PendingFetch::File { index }) {
+            return self.fail_staging(&e.to_string());
+        }
+        Ok(())
+    }
+
+    /// Bundle file downloaded: verify digest and write it into the slot.
+    fn on_file_fetched(&mut self, index: usize, body: &[u8]) -> Result<(), AppError> {
+        let (file, slot) = match self.staging.as_ref() {
+            Some(StagingJob {
+                manifest: Some(manifest),
+                slot,
+                ..
+            }) => match manifest.files.get(index) {
+                Some(file) => (file.clone(), *slot),
+                None => return self.fail_staging("Internal error: file index out of range"),
+            },
+            _ => return Ok(()),
+        };
+
+        if let Err(e) = BundleManifest::verify_file(&file, body) {
+            return self.fail_staging(&e.message());
+        }
+
+        let path = slot.file_path(&file.name);
+        if let Err(e) = self.start_vfs_write(&path, body, PendingOp::WriteBundleFile { index }) {
+            return self.fail_staging(&e.to_string());
+        }
+        Ok(())
+    }
+
+    /// Every file is written: persist the slot as pending.
+    fn commit_stage(&mut self) -> Result<(), AppError> {
+        let version = match self.staging.as_ref().and_then(|j| j.manifest.as_ref()) {
+            Some(m) => m.version.clone(),
+            None => return Ok(()),
+        };
+        let mut next = self.state.clone();
+        if let Err(e) = next.stage(&version) {
+            return self.fail_staging(e.message());
+        }
+        let value = next.to_json();
+        if let Err(e) = self.start_vfs_write(
+            slots::STATE_PATH,
+            &value,
+            PendingOp::CommitStage { state: next },
+        ) {
+            return self.fail_staging(&e.to_string());
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // Network Result Handler
+    // =========================================================================
+
+    /// Handle MSG_NET_RESULT
+    fn handle_net_result(&mut self, msg: &Message) -> Result<(), AppError> {
+        if msg.data.len() < 9 {
+            return Ok(());
+        }
+
+        let request_id = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
+        let result_type = msg.data[4];
+        let data_len =
+            u32::from_le_bytes([msg.data[5], msg.data[6], msg.data[7], msg.data[8]]) as usize;
+        let data = if data_len > 0 && msg.data.len() >= 9 + data_len {
+            &msg.data[9..9 + data_len]
+        } else {
+            &[]
+        };
+
+        let fetch = match self.pending_fetches.remove(&request_id) {
+            Some(f) => f,
+            None => return Ok(()),
+        };
+
+        let response: HttpResponse = if result_type == 0 && !data.is_empty() {
+            serde_json::from_slice(data)
+                .unwrap_or_else(|_| HttpResponse::err(NetworkError::Other("Parse error".into())))
+        } else {
+            HttpResponse::err(NetworkError::Other("Network error".into()))
+        };
+
+        let body = match response.result {
+            Ok(HttpSuccess { status, body, .. }) if (200..300).contains(&status) => body,
+            Ok(HttpSuccess { status, .. }) => {
+                return self.fail_staging(&format!("Bundle download failed: HTTP {}", status));
+            }
+            Err(e) => {
+                return self.fail_staging(&format!("Bundle download failed: {}", e.message()));
+            }
+        };
+
+        match fetch {
+            PendingFetch::Manifest => self.on_manifest_fetched(&body),
+            PendingFetch::File { index } => self.on_file_fetched(index, &body),
+        }
+    }
+
+    // =========================================================================
+    // VFS Response Handlers
+    // =========================================================================
+
+    /// Handle VFS read response (MSG_VFS_READ_RESPONSE)
+    fn handle_vfs_read_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Some(op) = self.take_pending_by_type(OpType::Read) else {
+            syscall::debug("UpdateService: VFS read response but no pending read");
+            return Ok(());
+        };
+        let result = async_client::parse_read_response(&msg.data);
+
+        match op {
+            PendingOp::LoadSigningKey => {
+                self.signing_key = result.ok().and_then(|d| bundle::parse_signing_key(&d));
+                if self.signing_key.is_none() {
+                    syscall::debug(&format!(
+                        "UpdateService: no valid signing key at {}, staging disabled",
+                        bundle::SIGNING_KEY_PATH
+                    ));
+                }
+                Ok(())
+            }
+            PendingOp::LoadState => {
+                self.state = match result {
+                    Ok(data) => SlotState::from_json(&data).unwrap_or_default(),
+                    Err(_) => SlotState::default(),
+                };
+                let outcome = self.state.begin_boot();
+                match &outcome {
+                    BootOutcome::Normal(slot) => syscall::debug(&format!(
+                        "UpdateService: booted slot {} ({})",
+                        slot.name(),
+                        self.state.active_version
+                    )),
+                    BootOutcome::Trial(slot) => syscall::debug(&format!(
+                        "UpdateService: trial boot of slot {}, awaiting health confirmation",
+                        slot.name()
+                    )),
+                    BootOutcome::RolledBack(slot) => syscall::debug(&format!(
+                        "UpdateService: ROLLBACK - staged update never became healthy, back on slot {}",
+                        slot.name()
+                    )),
+                }
+                self.state_loaded = true;
+
+                if outcome != BootOutcome::Normal(self.state.active) {
+                    self.start_vfs_mkdir("/system/update", PendingOp::PrepareStateDir)?;
+                }
+                self.replay_deferred()
+            }
+            _ => {
+                syscall::debug("UpdateService: Unexpected pending operation for read response");
+                Ok(())
+            }
+        }
+    }
+
+    /// Handle VFS mkdir response (MSG_VFS_MKDIR_RESPONSE)
+    fn handle_vfs_mkdir_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Some(op) = self.take_pending_by_type(OpType::Mkdir) else {
+            return Ok(());
+        };
+        // Already-existing directories report an error here; a genuinely
+        // missing directory surfaces as a failed write in the next step.
+        if let Err(e) = async_client::parse_mkdir_response(&msg.data) {
+            syscall::debug(&format!("UpdateService: mkdir: {}", e));
+        }
+
+        match op {
+            PendingOp::PrepareStateDir => {
+                let value = self.state.to_json();
+                self.start_vfs_write(slots::STATE_PATH, &value, PendingOp::PersistBootState)
+            }
+            PendingOp::PrepareSlot => self.stage_next_file(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle VFS write response (MSG_VFS_WRITE_RESPONSE)
+    fn handle_vfs_write_response(&mut self, msg: &Message) -> Result<(), AppError> {
+        let Some(op) = self.take_pending_by_type(OpType::Write) else {
+            syscall::debug("UpdateService: VFS write response but no pending write");
+            return Ok(());
+        };
+        let result = async_client::parse_write_response(&msg.data);
+
+        match op {
+            PendingOp::PersistBootState => {
+                if let Err(e) = result {
+                    // Rule 9: the boot counter did not persist, so a crash now
+                    // would not count towards rollback.
+                    syscall::debug(&format!(
+                        "UpdateService: failed to persist boot state to {}: {}",
+                        slots::STATE_PATH,
+                        e
+                    ));
+                }
+                Ok(())
+            }
+            PendingOp::ClearStaged => match result {
+                Ok(()) => {
+                    let root = match self.staging.as_ref() {
+                        Some(job) => job.slot.root(),
+                        None => return Ok(()),
+                    };
+                    if let Err(e) = self.start_vfs_mkdir(&root, PendingOp::PrepareSlot) {
+                        return self.fail_staging(&e.to_string());
+                    }
+                    Ok(())
+                }
+                Err(e) => self.fail_staging(&format!(
+                    "VFS write failed for {}: {}",
+                    slots::STATE_PATH,
+                    e
+                )),
+            },
+            PendingOp::WriteBundleFile { index } => match result {
+                Ok(()) => {
+                    if let Some(job) = self.staging.as_mut() {
+                        job.next_file = index + 1;
+                    }
+                    self.stage_next_file()
+                }
+                Err(e) => self.fail_staging(&format!(
+                    "VFS write failed for bundle file {}: {}",
+                    index, e
+                )),
+            },
+            PendingOp::WriteSlotManifest => match result {
+                Ok(()) => self.commit_stage(),
+                Err(e) => self.fail_staging(&format!("VFS write failed for slot manifest: {}", e)),
+            },
+            PendingOp::CommitStage { state } => match result {
+                Ok(()) => {
+                    self.state = state;
+                    let Some(job) = self.staging.take() else {
+                        return Ok(());
+                    };
+                    let version = job.manifest.map(|m| m.version).unwrap_or_default();
+                    syscall::debug(&format!(
+                        "UpdateService: {} staged in slot {}, active on next boot",
+                        version,
+                        job.slot.name()
+                    ));
+                    let json = format!(
+                        r#"{{"version":"{}","slot":"{}"}}"#,
+                        version.replace('"', "\\\""),
+                        job.slot.name()
+                    );
+                    self.send_response(
+                        job.client_pid,
+                        &job.cap_slots,
+                        update_msg::MSG_UPDATE_STAGE_RESPONSE,
+                        json.as_bytes(),
+                    )
+                }
+                Err(e) => self.fail_staging(&format!(
+                    "VFS write failed for {}: {}",
+                    slots::STATE_PATH,
+                    e
+                )),
+            },
+            PendingOp::CommitHealth {
+                client_pid,
+                cap_slots,
+                response_tag,
+                state,
+            } => match result {
+                Ok(()) => {
+                    self.state = state;
+                    let json = serde_json::to_vec(&self.status()).unwrap_or_default();
+                    self.send_response(client_pid, &cap_slots, response_tag, &json)
+                }
+                Err(e) => self.send_error_response(
+                    client_pid,
+                    &cap_slots,
+                    response_tag,
+                    &format!("VFS write failed for {}: {}", slots::STATE_PATH, e),
+                ),
+            },
+            _ => {
+                syscall::debug("UpdateService: Unexpected pending operation for write response");
+                Ok(())
+            }
+        }
+    }
+
+    /// Buffer a request until slot state is loaded.
+    fn defer(&mut self, msg: Message) -> Result<(), AppError> {
+        if self.deferred.len() >= MAX_DEFERRED_MESSAGES {
+            return self.send_error_response(
+                msg.from_pid,
+                &msg.cap_slots,
+                msg.tag + 1,
+                "Service busy: slot state still loading",
+            );
+        }
+        self.deferred.push(msg);
+        Ok(())
+    }
+
+    fn replay_deferred(&mut self) -> Result<(), AppError> {
+        for msg in core::mem::take(&mut self.deferred) {
+            self.dispatch_request(msg)?;
+        }
+        Ok(())
+    }
+
+    fn dispatch_request(&mut self, msg: Message) -> Result<(), AppError> {
+        if !self.state_loaded {
+            return self.defer(msg);
+        }
+        match msg.tag {
+            update_msg::MSG_UPDATE_STAGE => self.handle_stage(&msg),
+            update_msg::MSG_UPDATE_STATUS => self.handle_status(&msg),
+            update_msg::MSG_UPDATE_HEALTH_OK => self.handle_health(&msg, true),
+            update_msg::MSG_UPDATE_HEALTH_FAILED => self.handle_health(&msg, false),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl JsonResponder for UpdateService {
+    const SERVICE_NAME: &'static str = "UpdateService";
+}
+
+impl ZeroApp for UpdateService {
+    fn manifest() -> &'static zos_apps::AppManifest {
+        &UPDATE_MANIFEST
+    }
+
+    fn init(&mut self, ctx: &AppContext) -> Result<(), AppError> {
+        syscall::debug(&format!("UpdateService starting (PID {})", ctx.pid));
+
+        // Register with init as "update" service
+        let service_name = "update";
+        let name_bytes = service_name.as_bytes();
+        let mut data = Vec::with_capacity(1 + name_bytes.len() + 8);
+        data.push(name_bytes.len() as u8);
+        data.extend_from_slice(name_bytes);
+        // Endpoint ID (placeholder)
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        let _ = syscall::send(
+            syscall::INIT_ENDPOINT_SLOT,
+            syscall::MSG_REGISTER_SERVICE,
+            &data,
+        );
+        self.registered = true;
+
+        syscall::debug("UpdateService: Registered with init");
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+
+        // Load signing key and slot state (boot accounting happens on load)
+        self.start_vfs_read(bundle::SIGNING_KEY_PATH, PendingOp::LoadSigningKey)?;
+        self.start_vfs_read(slots::STATE_PATH, PendingOp::LoadState)?;
+
+        Ok(())
+    }
+
+    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+        ControlFlow::Yield
+    }
+
+    fn on_message(&mut self, _ctx: &AppContext, msg: Message) -> Result<(), AppError> {
+        syscall::debug(&format!(
+            "UpdateService: Received message tag 0x{:x} from PID {}",
+            msg.tag, msg.from_pid
+        ));
+
+        match msg.tag {
+            // VFS responses (Invariant 31 compliant - storage via VFS IPC)
+            vfs_msg::MSG_VFS_READ_RESPONSE => self.handle_vfs_read_response(&msg),
+            vfs_msg::MSG_VFS_WRITE_RESPONSE => self.handle_vfs_write_response(&msg),
+            vfs_msg::MSG_VFS_MKDIR_RESPONSE => self.handle_vfs_mkdir_response(&msg),
+
+            // Network results
+            net::MSG_NET_RESULT => self.handle_net_result(&msg),
+
+            // Update service protocol
+            update_msg::MSG_UPDATE_STAGE
+            | update_msg::MSG_UPDATE_STATUS
+            | update_msg::MSG_UPDATE_HEALTH_OK
+            | update_msg::MSG_UPDATE_HEALTH_FAILED => self.dispatch_request(msg),
+
+            _ => {
+                syscall::debug(&format!(
+                    "UpdateService: Unknown message tag 0x{:x} from PID {}",
+                    msg.tag, msg.from_pid
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _ctx: &AppContext) {
+        syscall::debug("UpdateService: shutting down");
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_message;
+
+    fn loaded() -> UpdateService {
+        UpdateService {
+            state_loaded: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stage_permission_trusted_pids() {
+        let service = UpdateService::default();
+        for &pid in TRUSTED_PIDS_FOR_STAGE {
+            assert!(service.check_stage_permission(pid));
+        }
+        assert!(!service.check_stage_permission(100));
+    }
+
+    #[test]
+    fn test_health_permission_restricted_to_boot_authorities() {
+        let service = UpdateService::default();
+        assert!(service.check_health_permission(0));
+        assert!(service.check_health_permission(1));
+        // Desktop may stage updates but must not vouch for boot health
+        assert!(!service.check_health_permission(3));
+    }
+
+    #[test]
+    fn test_pending_limit_counts_fetches_and_vfs_ops() {
+        let mut service = UpdateService::default();
+        for i in 0..MAX_PENDING_OPS / 2 {
+            service
+                .pending_ops
+                .insert(i as u32, (PendingOp::LoadState, OpType::Read));
+            service
+                .pending_fetches
+                .insert(i as u32, PendingFetch::Manifest);
+        }
+        assert!(!service.check_pending_limit());
+    }
+
+    #[test]
+    fn test_requests_deferred_until_state_loaded() {
+        let mut service = UpdateService::default();
+        let msg = mock_message(update_msg::MSG_UPDATE_STATUS, 3, Vec::new());
+        service.dispatch_request(msg).unwrap();
+        assert_eq!(service.deferred.len(), 1);
+    }
+
+    #[test]
+    fn test_take_pending_by_type_is_fifo() {
+        let mut service = loaded();
+        service
+            .pending_ops
+            .insert(5, (PendingOp::LoadSigningKey, OpType::Read));
+        service
+            .pending_ops
+            .insert(6, (PendingOp::PersistBootState, OpType::Write));
+        service
+            .pending_ops
+            .insert(7, (PendingOp::LoadState, OpType::Read));
+
+        assert!(matches!(
+            service.take_pending_by_type(OpType::Read),
+            Some(PendingOp::LoadSigningKey)
+        ));
+        assert!(matches!(
+            service.take_pending_by_type(OpType::Read),
+            Some(PendingOp::LoadState)
+        ));
+        assert!(service.take_pending_by_type(OpType::Read).is_none());
+        assert!(service.take_pending_by_type(OpType::Mkdir).is_none());
+    }
+
+    #[test]
+    fn test_status_reports_trial_slot() {
+        let mut service = loaded();
+        service.state.stage("2.0.0").unwrap();
+        service.state.begin_boot();
+        let status = service.status();
+        assert_eq!(status.booted, Slot::B);
+        assert_eq!(status.state.active, Slot::A);
+
+        let json = serde_json::to_string(&status).unwrap();
+        assert!(json.contains(r#""booted":"B""#));
+        assert!(json.contains(r#""active":"A""#));
+    }
+}
@@ -0,0 +1,341 @@
+//! A/B slot state machine
+//!
+//! System bundles live in two VFS slots (`/system/slots/a`, `/system/slots/b`).
+//! Exactly one slot is *active*; updates are always written to the other one.
+//!
+//! ```text
+//!   stage()            begin_boot()             confirm_healthy()
+//! Idle ───────► Staged ───────────► Trial ────────────────────► Idle (slots swapped)
+//!                                    │
+//!                                    │ report_unhealthy() / trial boot budget exhausted
+//!                                    ▼
+//!                                  Idle (active slot unchanged, rollback recorded)
+//! ```
+//!
+//! The active slot is only rewritten by `confirm_healthy()`, so a crash at any
+//! point during staging or a trial boot leaves the previous version bootable.
+
+use alloc::format;
+use alloc::string::String;
+use serde::{Deserialize, Serialize};
+
+/// Number of boots a staged slot gets to report healthy before rollback.
+pub const MAX_TRIAL_BOOTS: u32 = 2;
+
+/// VFS path where slot state is persisted.
+pub const STATE_PATH: &str = "/system/update/state.json";
+
+/// One of the two system bundle slots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Slot {
+    A,
+    B,
+}
+
+impl Slot {
+    /// The opposite slot.
+    pub fn other(self) -> Self {
+        match self {
+            Slot::A => Slot::B,
+            Slot::B => Slot::A,
+        }
+    }
+
+    /// Short lowercase name used in paths and responses.
+    pub fn name(self) -> &'static str {
+        match self {
+            Slot::A => "a",
+            Slot::B => "b",
+        }
+    }
+
+    /// VFS directory holding this slot's bundle.
+    pub fn root(self) -> String {
+        format!("/system/slots/{}", self.name())
+    }
+
+    /// VFS path of a bundle file inside this slot.
+    pub fn file_path(self, file_name: &str) -> String {
+        format!("{}/{}", self.root(), file_name)
+    }
+}
+
+/// A bundle staged into the inactive slot, awaiting a healthy boot.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingSlot {
+    /// Slot the bundle was written to
+    pub slot: Slot,
+    /// Bundle version
+    pub version: String,
+    /// Boots attempted from this slot so far (0 = not yet booted)
+    #[serde(default)]
+    pub boot_attempts: u32,
+}
+
+/// Record of the most recent automatic or reported rollback.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollbackRecord {
+    /// Version that was abandoned
+    pub version: String,
+    /// Why it was abandoned
+    pub reason: String,
+}
+
+/// What the current boot should run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BootOutcome {
+    /// No update pending, boot the active slot
+    Normal(Slot),
+    /// Trial boot of a staged slot, must be confirmed healthy
+    Trial(Slot),
+    /// Staged slot exhausted its trial boots, falling back to the active slot
+    RolledBack(Slot),
+}
+
+/// Errors from slot state transitions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SlotError {
+    /// A trial boot is in progress, it must be confirmed or rolled back first
+    TrialInProgress,
+    /// Health report received but no trial boot is in progress
+    NoTrialInProgress,
+}
+
+impl SlotError {
+    /// Human-readable error message.
+    pub fn message(&self) -> &'static str {
+        match self {
+            SlotError::TrialInProgress => "A trial boot is in progress",
+            SlotError::NoTrialInProgress => "No trial boot is in progress",
+        }
+    }
+}
+
+fn default_version() -> String {
+    String::from("factory")
+}
+
+/// Persistent A/B slot state.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotState {
+    /// Last slot confirmed healthy
+    pub active: Slot,
+    /// Version installed in the active slot
+    #[serde(default = "default_version")]
+    pub active_version: String,
+    /// Staged bundle awaiting (or undergoing) a trial boot
+    #[serde(default)]
+    pub pending: Option<PendingSlot>,
+    /// Most recent rollback, if any
+    #[serde(default)]
+    pub last_rollback: Option<RollbackRecord>,
+}
+
+impl Default for SlotState {
+    fn default() -> Self {
+        Self {
+            active: Slot::A,
+            active_version: default_version(),
+            pending: None,
+            last_rollback: None,
+        }
+    }
+}
+
+impl SlotState {
+    /// Serialize to JSON bytes.
+    pub fn to_json(&self) -> alloc::vec::Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes.
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+
+    /// Slot that new bundles are written to.
+    pub fn inactive(&self) -> Slot {
+        self.active.other()
+    }
+
+    /// Whether the current boot is running a staged slot on trial.
+    pub fn in_trial(&self) -> bool {
+        self.pending.as_ref().is_some_and(|p| p.boot_attempts > 0)
+    }
+
+    /// Slot the current boot is running from.
+    pub fn booted_slot(&self) -> Slot {
+        match &self.pending {
+            Some(p) if p.boot_attempts > 0 => p.slot,
+            _ => self.active,
+        }
+    }
+
+    /// Record a fully downloaded and verified bundle in the inactive slot.
+    ///
+    /// Replaces any previously staged (but not yet booted) bundle.
+    pub fn stage(&mut self, version: &str) -> Result<Slot, SlotError> {
+        if self.in_trial() {
+            return Err(SlotError::TrialInProgress);
+        }
+        let slot = self.inactive();
+        self.pending = Some(PendingSlot {
+            slot,
+            version: String::from(version),
+            boot_attempts: 0,
+        });
+        Ok(slot)
+    }
+
+    /// Account for a boot. Must be called once per boot, before services
+    /// from the booted slot are trusted.
+    pub fn begin_boot(&mut self) -> BootOutcome {
+        let Some(pending) = self.pending.as_mut() else {
+            return BootOutcome::Normal(self.active);
+        };
+
+        if pending.boot_attempts >= MAX_TRIAL_BOOTS {
+            let version = pending.version.clone();
+            self.abandon(
+                version,
+                format!("no healthy boot after {} attempts", MAX_TRIAL_BOOTS),
+            );
+            return BootOutcome::RolledBack(self.active);
+        }
+
+        pending.boot_attempts += 1;
+        BootOutcome::Trial(pending.slot)
+    }
+
+    /// Commit the trial slot as active.
+    pub fn confirm_healthy(&mut self) -> Result<Slot, SlotError> {
+        if !self.in_trial() {
+            return Err(SlotError::NoTrialInProgress);
+        }
+        if let Some(pending) = self.pending.take() {
+            self.active = pending.slot;
+            self.active_version = pending.version;
+            self.last_rollback = None;
+        }
+        Ok(self.active)
+    }
+
+    /// Abandon the trial slot; the next boot returns to the active slot.
+    pub fn report_unhealthy(&mut self, reason: &str) -> Result<Slot, SlotError> {
+        let version = match &self.pending {
+            Some(p) if p.boot_attempts > 0 => p.version.clone(),
+            _ => return Err(SlotError::NoTrialInProgress),
+        };
+        self.abandon(version, String::from(reason));
+        Ok(self.active)
+    }
+
+    fn abandon(&mut self, version: String, reason: String) {
+        self.pending = None;
+        self.last_rollback = Some(RollbackRecord { version, reason });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn staged() -> SlotState {
+        let mut state = SlotState::default();
+        state.stage("2.0.0").unwrap();
+        state
+    }
+
+    #[test]
+    fn test_default_boots_slot_a() {
+        let mut state = SlotState::default();
+        assert_eq!(state.begin_boot(), BootOutcome::Normal(Slot::A));
+        assert_eq!(state.booted_slot(), Slot::A);
+    }
+
+    #[test]
+    fn test_stage_targets_inactive_slot() {
+        let state = staged();
+        let pending = state.pending.as_ref().unwrap();
+        assert_eq!(pending.slot, Slot::B);
+        assert_eq!(pending.boot_attempts, 0);
+        // Still booted from A until the next boot
+        assert_eq!(state.booted_slot(), Slot::A);
+    }
+
+    #[test]
+    fn test_trial_boot_then_confirm_swaps_slots() {
+        let mut state = staged();
+        assert_eq!(state.begin_boot(), BootOutcome::Trial(Slot::B));
+        assert_eq!(state.booted_slot(), Slot::B);
+
+        assert_eq!(state.confirm_healthy(), Ok(Slot::B));
+        assert_eq!(state.active, Slot::B);
+        assert_eq!(state.active_version, "2.0.0");
+        assert!(state.pending.is_none());
+
+        // Next update goes back to slot A
+        assert_eq!(state.stage("3.0.0"), Ok(Slot::A));
+    }
+
+    #[test]
+    fn test_rollback_after_exhausting_trial_boots() {
+        let mut state = staged();
+        for _ in 0..MAX_TRIAL_BOOTS {
+            assert_eq!(state.begin_boot(), BootOutcome::Trial(Slot::B));
+        }
+        assert_eq!(state.begin_boot(), BootOutcome::RolledBack(Slot::A));
+        assert_eq!(state.active, Slot::A);
+        assert_eq!(state.active_version, "factory");
+        assert_eq!(state.last_rollback.as_ref().unwrap().version, "2.0.0");
+    }
+
+    #[test]
+    fn test_report_unhealthy_rolls_back() {
+        let mut state = staged();
+        state.begin_boot();
+        assert_eq!(
+            state.report_unhealthy("vfs failed to register"),
+            Ok(Slot::A)
+        );
+        assert!(state.pending.is_none());
+        assert_eq!(
+            state.last_rollback.as_ref().unwrap().reason,
+            "vfs failed to register"
+        );
+        assert_eq!(state.begin_boot(), BootOutcome::Normal(Slot::A));
+    }
+
+    #[test]
+    fn test_health_reports_require_trial() {
+        let mut state = staged();
+        assert_eq!(state.confirm_healthy(), Err(SlotError::NoTrialInProgress));
+        assert_eq!(
+            state.report_unhealthy("x"),
+            Err(SlotError::NoTrialInProgress)
+        );
+    }
+
+    #[test]
+    fn test_stage_rejected_during_trial() {
+        let mut state = staged();
+        state.begin_boot();
+        assert_eq!(state.stage("2.0.1"), Err(SlotError::TrialInProgress));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut state = staged();
+        state.begin_boot();
+        let parsed = SlotState::from_json(&state.to_json()).unwrap();
+        assert_eq!(parsed, state);
+    }
+
+    #[test]
+    fn test_from_minimal_json_uses_defaults() {
+        let parsed = SlotState::from_json(br#"{"active":"B"}"#).unwrap();
+        assert_eq!(parsed.active, Slot::B);
+        assert_eq!(parsed.active_version, "factory");
+        assert!(parsed.pending.is_none());
+    }
+}
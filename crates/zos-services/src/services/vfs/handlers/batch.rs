@@ -0,0 +1,295 @@
+//! Batched requests for VFS Service
+//!
+//! Handles: batch
+//!
+//! MSG_VFS_BATCH carries several operations in one message and is answered
+//! with one MSG_VFS_BATCH_RESPONSE, so listing the stats of a directory
+//! costs a single round-trip instead of one per entry.
+//!
+//! Each operation is handled as if the client had sent it on its own: the
+//! same permission checks, mount routing and deadline apply. Its response
+//! is recorded in the batch instead of being sent, and the batch response
+//! goes out once every operation has answered. Operations succeed or fail
+//! individually; only a malformed or oversized batch is refused as a whole.
+//!
+//! Operations run concurrently and may complete in any order, so a batch
+//! must not rely on one operation seeing the effect of another.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: one response per batch, with one entry per operation in
+//!   request order
+//! - **Forbidden**: nested batches; more than `MAX_BATCH_OPS` operations
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, Message, ZeroApp};
+use zos_vfs::ipc::{vfs_msg, BatchEntry, BatchRequest, BatchResponse};
+use zos_vfs::VfsError;
+
+use super::super::{ClientContext, VfsService};
+use super::checkpoint::InterruptedResponse;
+
+/// Maximum operations in one batch (Rule 11: resource limits)
+pub const MAX_BATCH_OPS: usize = 64;
+
+/// Maximum batches awaiting responses (Rule 11: resource limits)
+pub const MAX_PENDING_BATCHES: usize = 32;
+
+/// Where an operation's response goes in its batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchSlot {
+    /// Batch ID
+    pub id: u32,
+    /// Position of the operation in the batch
+    pub index: usize,
+    /// Tag the operation answers with
+    pub response_tag: u32,
+}
+
+/// A batch with operations still to answer.
+struct PendingBatch {
+    /// The client that sent the batch
+    ctx: ClientContext,
+    /// Responses so far, in request order
+    entries: Vec<Option<BatchEntry>>,
+    /// Deadline of the batch request, if the client set one
+    deadline: Option<u64>,
+}
+
+/// Batches awaiting responses.
+#[derive(Default)]
+pub struct BatchTable {
+    next_id: u32,
+    batches: BTreeMap<u32, PendingBatch>,
+}
+
+impl BatchTable {
+    /// Start a batch of `len` operations for `ctx`.
+    pub fn start(
+        &mut self,
+        ctx: ClientContext,
+        len: usize,
+        deadline: Option<u64>,
+    ) -> Result<u32, VfsError> {
+        if self.batches.len() >= MAX_PENDING_BATCHES {
+            return Err(VfsError::retry("Too many pending batches"));
+        }
+        self.next_id = self.next_id.wrapping_add(1);
+        let entries = (0..len).map(|_| None).collect();
+        self.batches.insert(
+            self.next_id,
+            PendingBatch {
+                ctx,
+                entries,
+                deadline,
+            },
+        );
+        Ok(self.next_id)
+    }
+
+    /// Whether the operation in `slot` has yet to answer.
+    pub fn is_waiting(&self, slot: &BatchSlot) -> bool {
+        self.batches
+            .get(&slot.id)
+            .and_then(|batch| batch.entries.get(slot.index))
+            .is_some_and(Option::is_none)
+    }
+
+    /// Record the response of the operation in `slot`.
+    ///
+    /// Returns the client and the entries once the whole batch has
+    /// answered. Responses for batches that are gone, and second responses
+    /// for the same slot, are dropped.
+    pub fn record(
+        &mut self,
+        slot: &BatchSlot,
+        entry: BatchEntry,
+    ) -> Option<(ClientContext, Vec<BatchEntry>)> {
+        let batch = self.batches.get_mut(&slot.id)?;
+        match batch.entries.get_mut(slot.index) {
+            Some(cell) if cell.is_none() => *cell = Some(entry),
+            _ => return None,
+        }
+        if batch.entries.iter().any(Option::is_none) {
+            return None;
+        }
+        let batch = self.batches.remove(&slot.id)?;
+        Some((batch.ctx, batch.entries.into_iter().flatten().collect()))
+    }
+
+    /// Drop `pid`'s batches. Returns how many were dropped.
+    pub fn remove_client(&mut self, pid: u32) -> usize {
+        let before = self.batches.len();
+        self.batches.retain(|_, batch| batch.ctx.pid != pid);
+        before - self.batches.len()
+    }
+
+    /// Drop batches whose deadline has passed. Returns how many were dropped.
+    pub fn expire(&mut self, now_ns: u64) -> usize {
+        let before = self.batches.len();
+        self.batches
+            .retain(|_, batch| batch.deadline.is_none_or(|d| d > now_ns));
+        before - self.batches.len()
+    }
+
+    /// Number of batches awaiting responses.
+    pub fn len(&self) -> usize {
+        self.batches.len()
+    }
+
+    /// Whether no batch is awaiting responses.
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+
+    /// The response recorded for the operation in `slot`, if any.
+    #[cfg(test)]
+    pub fn entry(&self, slot: &BatchSlot) -> Option<&BatchEntry> {
+        self.batches
+            .get(&slot.id)
+            .and_then(|batch| batch.entries.get(slot.index))
+            .and_then(Option::as_ref)
+    }
+}
+
+impl VfsService {
+    /// Handle MSG_VFS_BATCH - run several operations, answer once
+    pub fn handle_batch(&mut self, ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = ClientContext::from_message(msg);
+        let request = match serde_json::from_slice::<BatchRequest>(&msg.data) {
+            Ok(r) => r,
+            Err(e) => {
+                return self.send_batch_error(
+                    &client_ctx,
+                    VfsError::InvalidRequest(format!("Failed to parse request: {}", e)),
+                );
+            }
+        };
+        if request.ops.len() > MAX_BATCH_OPS {
+            return self.send_batch_error(
+                &client_ctx,
+                VfsError::InvalidRequest(format!(
+                    "Too many operations in batch (max {})",
+                    MAX_BATCH_OPS
+                )),
+            );
+        }
+        if request.ops.is_empty() {
+            let response = BatchResponse {
+                result: Ok(Vec::new()),
+            };
+            return self.send_response(&client_ctx, vfs_msg::MSG_VFS_BATCH_RESPONSE, &response);
+        }
+
+        let started = self.batches.borrow_mut().start(
+            client_ctx.clone(),
+            request.ops.len(),
+            self.request_deadline,
+        );
+        let id = match started {
+            Ok(id) => id,
+            Err(e) => return self.send_batch_error(&client_ctx, e),
+        };
+        syscall::debug(&format!(
+            "VfsService: Batch {} of {} operation(s) from PID {}",
+            id,
+            request.ops.len(),
+            msg.from_pid
+        ));
+
+        for (index, op) in request.ops.iter().enumerate() {
+            let slot = BatchSlot {
+                id,
+                index,
+                response_tag: op.request_tag() + 1,
+            };
+            let op_msg = Message {
+                tag: op.request_tag(),
+                from_pid: msg.from_pid,
+                cap_slots: Vec::new(),
+                correlation_id: None,
+                data: op.request_data(),
+            };
+            let op_ctx = ClientContext {
+                pid: msg.from_pid,
+                reply_caps: Vec::new(),
+                batch: Some(slot.clone()),
+                correlation_id: None,
+            };
+
+            self.batch_ctx = Some(op_ctx.clone());
+            let result = self.on_message(ctx, op_msg);
+            self.batch_ctx = None;
+
+            // A handler that failed without answering leaves no response to
+            // wait for
+            if let Err(e) = result {
+                if self.batches.borrow().is_waiting(&slot) {
+                    let response = InterruptedResponse {
+                        result: Err(VfsError::StorageError(format!("{:?}", e))),
+                    };
+                    self.send_response(&op_ctx, slot.response_tag, &response)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The client context for a request, which is the batch operation's
+    /// while one is being handled.
+    pub(in crate::services::vfs) fn client_context(&self, msg: &Message) -> ClientContext {
+        match &self.batch_ctx {
+            Some(ctx) if ctx.pid == msg.from_pid => ctx.clone(),
+            _ => ClientContext::from_message(msg),
+        }
+    }
+
+    /// The batch operation a response sent to `to_pid` with `tag` answers,
+    /// if one is being handled.
+    pub(in crate::services::vfs) fn batch_reply_to(
+        &self,
+        to_pid: u32,
+        tag: u32,
+    ) -> Option<&ClientContext> {
+        self.batch_ctx.as_ref().filter(|ctx| {
+            ctx.pid == to_pid && ctx.batch.as_ref().is_some_and(|s| s.response_tag == tag)
+        })
+    }
+
+    /// Record a batch operation's response, sending the batch response if
+    /// it was the last one.
+    pub(in crate::services::vfs) fn record_batch_entry(
+        &self,
+        slot: &BatchSlot,
+        tag: u32,
+        data: Vec<u8>,
+    ) -> Result<(), AppError> {
+        let entry = BatchEntry {
+            tag,
+            response: String::from_utf8(data).unwrap_or_default(),
+        };
+        let done = self.batches.borrow_mut().record(slot, entry);
+        match done {
+            Some((ctx, entries)) => {
+                let response = BatchResponse {
+                    result: Ok(entries),
+                };
+                self.send_response(&ctx, vfs_msg::MSG_VFS_BATCH_RESPONSE, &response)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn send_batch_error(
+        &self,
+        client_ctx: &ClientContext,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        let response = BatchResponse { result: Err(error) };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_BATCH_RESPONSE, &response)
+    }
+}
@@ -0,0 +1,137 @@
+//! Caller identity for VFS Service
+//!
+//! Handles: set caller user, and the user each request is checked as
+//!
+//! Applications are checked against file permissions as the session user
+//! (the user signed in on this machine), never as a user named in the path
+//! they ask for. With nobody signed in they get world permissions only, and
+//! the files they create belong to nobody.
+//!
+//! The session user is set with MSG_VFS_SET_CALLER_USER, accepted only from
+//! Init, the PermissionManager and the IdentityService, which sets it when a
+//! user logs in and clears it on logout. It is saved with the service
+//! checkpoint.
+//!
+//! Init is the superuser and passes every permission check. Other system
+//! processes (the supervisor and boot services, by the name they run under)
+//! follow the system rules of `zos_vfs::service`, acting for the user named
+//! in the path.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: an application's requests are checked as the session user
+//! - **Forbidden**: an application acting as any other user; the session
+//!   user set by any other process
+
+use alloc::format;
+use alloc::string::String;
+use serde::{Deserialize, Serialize};
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_process::services;
+use zos_vfs::core::UserId;
+use zos_vfs::ipc::{vfs_msg, SetCallerUserRequest, SetCallerUserResponse};
+use zos_vfs::VfsError;
+
+use super::super::VfsService;
+
+/// Services that may set the session user, besides Init.
+const BINDING_SERVICES: &[&str] = &[services::PERMISSION, services::IDENTITY];
+
+/// The user applications act as.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "SavedCallers", into = "SavedCallers")]
+pub struct CallerUsers {
+    /// User signed in on this machine
+    session: Option<UserId>,
+}
+
+/// Checkpointed form of [`CallerUsers`]
+///
+/// The user ID is hex: checkpoints are decoded through `serde_json::Value`,
+/// which can't hold a full u128.
+#[derive(Default, Serialize, Deserialize)]
+struct SavedCallers {
+    #[serde(default)]
+    session: Option<String>,
+}
+
+impl From<CallerUsers> for SavedCallers {
+    fn from(callers: CallerUsers) -> Self {
+        Self {
+            session: callers.session.map(|user_id| format!("{:x}", user_id)),
+        }
+    }
+}
+
+impl From<SavedCallers> for CallerUsers {
+    fn from(saved: SavedCallers) -> Self {
+        Self {
+            session: saved
+                .session
+                .and_then(|hex| UserId::from_str_radix(&hex, 16).ok()),
+        }
+    }
+}
+
+impl CallerUsers {
+    /// The user applications act as, if anyone is signed in.
+    pub fn user(&self) -> Option<UserId> {
+        self.session
+    }
+
+    /// Set the session user, or clear it if `user_id` is `None`.
+    pub fn set(&mut self, user_id: Option<UserId>) {
+        self.session = user_id;
+    }
+
+    /// Take on the session user saved by a previous run, unless one was set since.
+    pub fn restore(&mut self, saved: CallerUsers) {
+        if self.session.is_none() {
+            self.session = saved.session;
+        }
+    }
+}
+
+impl VfsService {
+    /// Whether `pid` may set the session user.
+    fn may_set_caller_user(&self, pid: u32) -> bool {
+        pid == zos_process::pid::INIT
+            || self
+                .names
+                .name_of(pid)
+                .is_some_and(|name| BINDING_SERVICES.contains(&name.as_str()))
+    }
+
+    /// Handle MSG_VFS_SET_CALLER_USER - set or clear the session user
+    pub fn handle_set_caller_user(&mut self, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let result = if !self.may_set_caller_user(msg.from_pid) {
+            syscall::debug(&format!(
+                "VfsService: Refusing caller user change from PID {}",
+                msg.from_pid
+            ));
+            Err(VfsError::PermissionDenied)
+        } else {
+            match serde_json::from_slice::<SetCallerUserRequest>(&msg.data) {
+                Ok(request) => {
+                    syscall::debug(&format!(
+                        "VfsService: Session user now {:?}",
+                        request.user_id.map(|id| format!("{:032x}", id))
+                    ));
+                    self.callers.set(request.user_id);
+                    Ok(())
+                }
+                Err(e) => Err(VfsError::InvalidRequest(format!(
+                    "Failed to parse request: {}",
+                    e
+                ))),
+            }
+        };
+        self.send_response(
+            &client_ctx,
+            vfs_msg::MSG_VFS_SET_CALLER_USER_RESPONSE,
+            &SetCallerUserResponse { result },
+        )
+    }
+}
@@ -0,0 +1,100 @@
+//! Request cancellation
+//!
+//! A client that no longer wants a response (its window closed, the user
+//! navigated away) sends `MSG_CANCEL_REQUEST` naming the request by its tag.
+//! Pending operations working for that request are dropped, the storage HAL
+//! is asked to abort them, and the client gets the usual response carrying
+//! `VfsError::Cancelled`.
+//!
+//! Operations are matched through the response tag they would answer with,
+//! which is the request tag plus one. Intermediate steps that answer nobody
+//! are left to finish. The operations of a batch answer with the batch, so
+//! they are cancelled together by cancelling `MSG_VFS_BATCH`.
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_ipc::request::CANCEL_ALL;
+use zos_vfs::ipc::vfs_msg;
+use zos_vfs::VfsError;
+
+use super::super::VfsService;
+use super::checkpoint::InterruptedResponse;
+
+impl VfsService {
+    /// Handle MSG_CANCEL_REQUEST from a client.
+    pub fn handle_cancel(&mut self, msg: &Message) -> Result<(), AppError> {
+        if msg.data.len() < 4 {
+            syscall::debug("VfsService: cancel request too short");
+            return Ok(());
+        }
+        let tag = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
+
+        let replies = self.cancel_pending_ops(msg.from_pid, tag);
+        let response = InterruptedResponse {
+            result: Err(VfsError::Cancelled),
+        };
+        for (pid, response_tag) in replies {
+            let _ = self.send_response_via_debug(pid, response_tag, &response);
+        }
+        Ok(())
+    }
+
+    /// Drop `pid`'s pending operations for requests sent with `tag`
+    /// (`CANCEL_ALL` for every request) and abort their storage requests.
+    ///
+    /// Returns the responses the client is still owed.
+    pub fn cancel_pending_ops(&mut self, pid: u32, tag: u32) -> BTreeSet<(u32, u32)> {
+        let selected = |(client, response_tag): (u32, u32)| {
+            client == pid && (tag == CANCEL_ALL || response_tag == tag.wrapping_add(1))
+        };
+        let cancelled: Vec<(u32, (u32, u32))> = self
+            .pending_ops
+            .iter()
+            .filter_map(|(&request_id, op)| op.client_reply().map(|reply| (request_id, reply)))
+            .filter(|&(_, reply)| selected(reply))
+            .collect();
+
+        let mut replies = BTreeSet::new();
+        for (request_id, reply) in cancelled {
+            self.pending_ops.remove(&request_id);
+            self.op_deadlines.remove(&request_id);
+            if let Err(e) = syscall::storage_cancel(request_id) {
+                // Already completed; the late result is dropped as unknown
+                syscall::debug(&format!(
+                    "VfsService: storage_cancel({}) failed: {}",
+                    request_id, e
+                ));
+            }
+            replies.insert(reply);
+        }
+
+        // Results parked on a content key have no storage request to abort
+        let mut parked = Vec::new();
+        self.content_keys.remove_waiting(|op| match op.client_reply() {
+            Some(reply) if selected(reply) => {
+                parked.push(reply);
+                true
+            }
+            _ => false,
+        });
+        replies.extend(parked);
+
+        // Batches answer once, whichever of their operations were cancelled
+        if (tag == CANCEL_ALL || tag == vfs_msg::MSG_VFS_BATCH)
+            && self.batches.get_mut().remove_client(pid) > 0
+        {
+            replies.insert((pid, vfs_msg::MSG_VFS_BATCH_RESPONSE));
+        }
+        if !replies.is_empty() {
+            syscall::debug(&format!(
+                "VfsService: Cancelled {} request(s) for PID {}",
+                replies.len(),
+                pid
+            ));
+        }
+        replies
+    }
+}
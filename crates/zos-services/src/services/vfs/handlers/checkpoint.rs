@@ -0,0 +1,297 @@
+//! Service state checkpointing
+//!
+//! VfsService checkpoints four things so a restart is not silent:
+//!
+//! - **Migration sweep progress**: the sweep resumes where it stopped
+//!   instead of rescanning the whole tree.
+//! - **Interrupted client requests**: clients that were waiting on a
+//!   response when the service died get a storage error instead of
+//!   waiting forever.
+//! - **Pinned service keys**: a restart neither lets another key claim a
+//!   service name nor replays an already accepted signed request.
+//! - **Storage usage**: per-user quota counters, which are only ever
+//!   updated incrementally and can't be rebuilt without a full scan.
+//!
+//! The checkpoint is kept under a raw storage key, since VfsService cannot
+//! use VFS IPC to reach itself. It is read in `init`; nothing is written
+//! until that read completes.
+
+use alloc::format;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zos_apps::syscall;
+use zos_apps::{AppError, Restored, ServiceState};
+use zos_ipc::storage::ResultKind;
+use zos_vfs::ipc::vfs_msg;
+use zos_vfs::{StorageErrorKind, VfsError};
+
+use super::super::{ClientContext, InodeOpType, PendingOp, VfsService};
+use super::callers::CallerUsers;
+use super::migrate::SweepProgress;
+use super::quota::QuotaTable;
+use crate::signing::TrustedServiceKeys;
+
+/// State saved across VfsService restarts.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VfsState {
+    /// Migration sweep progress (`None` if the sweep had not started)
+    pub migration: Option<SweepProgress>,
+    /// Client requests awaiting a response
+    pub interrupted: Vec<InterruptedRequest>,
+    /// Pinned service signing keys and their replay state
+    #[serde(default)]
+    pub service_keys: TrustedServiceKeys,
+    /// Per-user storage usage
+    #[serde(default)]
+    pub quotas: QuotaTable,
+    /// The user each application process acts as
+    #[serde(default)]
+    pub callers: CallerUsers,
+}
+
+impl ServiceState for VfsState {
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// A client request that was in flight at the last checkpoint.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct InterruptedRequest {
+    pub pid: u32,
+    pub response_tag: u32,
+}
+
+/// Error response for a request lost in (or refused ahead of) a restart.
+///
+/// Every VFS response is `{ result }`, so the `Err` form is the same
+/// whatever the request was.
+#[derive(Serialize)]
+pub(super) struct InterruptedResponse {
+    pub(super) result: Result<(), VfsError>,
+}
+
+impl PendingOp {
+    /// The client waiting on this operation and the response tag it expects.
+    ///
+    /// `None` for intermediate steps and internal operations.
+    pub fn client_reply(&self) -> Option<(u32, u32)> {
+        let (ctx, tag) = self.client()?;
+        // Batch operations are answered through their batch
+        let tag = match ctx.batch {
+            Some(_) => vfs_msg::MSG_VFS_BATCH_RESPONSE,
+            None => tag,
+        };
+        Some((ctx.pid, tag))
+    }
+
+    /// The client this operation answers and the tag of the response, as
+    /// the operation would send it (within its batch, if any).
+    pub fn client(&self) -> Option<(&ClientContext, u32)> {
+        let (ctx, tag) = match self {
+            PendingOp::GetInode { ctx, op_type, .. } => {
+                let tag = match op_type {
+                    InodeOpType::Stat => vfs_msg::MSG_VFS_STAT_RESPONSE,
+                    InodeOpType::Exists => vfs_msg::MSG_VFS_EXISTS_RESPONSE,
+                    InodeOpType::ReadFile => vfs_msg::MSG_VFS_READ_RESPONSE,
+                    InodeOpType::MkdirCheckParent { .. } => vfs_msg::MSG_VFS_MKDIR_RESPONSE,
+                    InodeOpType::WriteFileCheckParent { .. } => vfs_msg::MSG_VFS_WRITE_RESPONSE,
+                    InodeOpType::Rmdir { .. } => vfs_msg::MSG_VFS_RMDIR_RESPONSE,
+                    InodeOpType::Unlink => vfs_msg::MSG_VFS_UNLINK_RESPONSE,
+                    InodeOpType::Readdir => vfs_msg::MSG_VFS_READDIR_RESPONSE,
+                };
+                (ctx, tag)
+            }
+            PendingOp::GetContent { ctx, .. } => (ctx, vfs_msg::MSG_VFS_READ_RESPONSE),
+            PendingOp::PutInode {
+                ctx: Some(ctx),
+                response_tag,
+            }
+            | PendingOp::DeleteInode {
+                ctx: Some(ctx),
+                response_tag,
+            } => (ctx, *response_tag),
+            PendingOp::PutContent { ctx, .. } => (ctx, vfs_msg::MSG_VFS_WRITE_RESPONSE),
+            PendingOp::WriteFileOp { ctx, reply, .. } => (ctx, reply.response_tag()),
+            PendingOp::OpenFileOp { ctx, .. } => (ctx, vfs_msg::MSG_VFS_OPEN_RESPONSE),
+            PendingOp::ReadAtOp { ctx, .. } => (ctx, vfs_msg::MSG_VFS_READ_AT_RESPONSE),
+            PendingOp::WatchOp { ctx, .. } => (ctx, vfs_msg::MSG_VFS_WATCH_RESPONSE),
+            PendingOp::WriteAtOp { ctx, .. } | PendingOp::ChunkPatchOp { ctx, .. } => {
+                (ctx, vfs_msg::MSG_VFS_WRITE_AT_RESPONSE)
+            }
+            PendingOp::HashOp { ctx, .. } => (ctx, vfs_msg::MSG_VFS_HASH_RESPONSE),
+            PendingOp::ChunkReadOp { ctx, read } => (ctx, read.reply.response_tag()),
+            PendingOp::ContentDeleteOp { then, .. } | PendingOp::FollowLink { then } => {
+                return then.client()
+            }
+            PendingOp::ListChildren { ctx, .. } | PendingOp::ReaddirOp { ctx, .. } => {
+                (ctx, vfs_msg::MSG_VFS_READDIR_RESPONSE)
+            }
+            PendingOp::ExistsCheck { ctx, .. } => (ctx, vfs_msg::MSG_VFS_EXISTS_RESPONSE),
+            PendingOp::CheckExistsForMkdir { ctx, .. } | PendingOp::MkdirOp { ctx, .. } => {
+                (ctx, vfs_msg::MSG_VFS_MKDIR_RESPONSE)
+            }
+            PendingOp::UnlinkOp { ctx, .. } => (ctx, vfs_msg::MSG_VFS_UNLINK_RESPONSE),
+            PendingOp::RenameOp { ctx, reply, .. } => (ctx, reply.response_tag()),
+            PendingOp::LinkOp { ctx, .. } => (ctx, vfs_msg::MSG_VFS_LINK_RESPONSE),
+            PendingOp::RmdirOp { ctx, .. } => (ctx, vfs_msg::MSG_VFS_RMDIR_RESPONSE),
+            PendingOp::XattrOp { ctx, request, .. } => (ctx, request.response_tag()),
+            PendingOp::TrashOp {
+                ctx: Some(ctx),
+                request,
+                ..
+            } => (ctx, request.response_tag()),
+            PendingOp::SnapshotOp { ctx, job, .. } => (ctx, job.request.response_tag()),
+            PendingOp::PutInode { ctx: None, .. }
+            | PendingOp::DeleteInode { ctx: None, .. }
+            | PendingOp::DeleteContent { .. }
+            | PendingOp::ReleaseChunks { .. }
+            | PendingOp::ReleaseBlob { .. }
+            | PendingOp::GcOp { .. }
+            | PendingOp::TrashOp { ctx: None, .. }
+            | PendingOp::TrashSweepHomes
+            | PendingOp::MigrateInode { .. }
+            | PendingOp::MigrateList { .. }
+            | PendingOp::MigrateWrite { .. }
+            | PendingOp::MigrateSeal { .. }
+            | PendingOp::RestoreState
+            | PendingOp::LoadFlags
+            | PendingOp::CheckpointState => return None,
+        };
+        Some((ctx, tag))
+    }
+}
+
+impl VfsService {
+    /// Read the checkpoint left by a previous run.
+    pub fn start_state_restore(&mut self) {
+        let key = self.checkpoint.location();
+        if self
+            .start_storage_read(&key, PendingOp::RestoreState)
+            .is_err()
+        {
+            // No storage - run without restoring
+            self.finish_state_restore(None);
+        }
+    }
+
+    /// Checkpoint read completed.
+    pub fn handle_restore_state_result(
+        &mut self,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        match result_type {
+            ResultKind::ReadOk => self.finish_state_restore(Some(data)),
+            ResultKind::NotFound => self.finish_state_restore(None),
+            _ => {
+                syscall::debug(&format!(
+                    "VfsService: Checkpoint read failed: {} ({}), starting fresh",
+                    result_type as u8,
+                    result_type.name()
+                ));
+                self.finish_state_restore(None);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish_state_restore(&mut self, data: Option<&[u8]>) {
+        let outcome = self.checkpoint.restore(data);
+        syscall::debug(&format!("VfsService: State restore: {:?}", outcome));
+
+        let state = self.checkpoint.state().clone();
+        self.service_keys.restore(state.service_keys.clone());
+        self.quotas.restore(state.quotas.clone());
+        self.callers.restore(state.callers.clone());
+        if outcome != Restored::Fresh {
+            self.notify_interrupted_requests(&state.interrupted);
+        }
+        match state.migration {
+            Some(progress) => self.resume_migration_sweep(progress),
+            None => self.start_migration_sweep(),
+        }
+    }
+
+    /// Tell clients of the previous run that their requests were lost.
+    fn notify_interrupted_requests(&self, interrupted: &[InterruptedRequest]) {
+        let response = InterruptedResponse {
+            result: Err(VfsError::storage_error_with_context(
+                StorageErrorKind::Unavailable,
+                "VFS service restarted",
+            )),
+        };
+        for request in interrupted {
+            syscall::debug(&format!(
+                "VfsService: Failing request interrupted by restart (PID {}, tag 0x{:x})",
+                request.pid, request.response_tag
+            ));
+            let _ = self.send_response_via_debug(request.pid, request.response_tag, &response);
+        }
+    }
+
+    /// Current state as it would be checkpointed.
+    fn state_snapshot(&self) -> VfsState {
+        let mut interrupted: Vec<InterruptedRequest> = self
+            .pending_ops
+            .values()
+            .chain(self.content_keys.waiting_ops())
+            .filter_map(PendingOp::client_reply)
+            .map(|(pid, response_tag)| InterruptedRequest { pid, response_tag })
+            .collect();
+        interrupted.sort();
+        // The operations of a batch share its one response
+        interrupted.dedup();
+        VfsState {
+            migration: self.migration_progress(),
+            interrupted,
+            service_keys: self.service_keys.clone(),
+            quotas: self.quotas.clone(),
+            callers: self.callers.clone(),
+        }
+    }
+
+    /// Write a checkpoint if the state changed and one is due.
+    pub fn pump_checkpoint(&mut self, now_ms: u64) {
+        if !self.checkpoint.is_restored() {
+            return;
+        }
+        let snapshot = self.state_snapshot();
+        if *self.checkpoint.state() != snapshot {
+            *self.checkpoint.state_mut() = snapshot;
+        }
+        if !self.checkpoint.checkpoint_due(now_ms) {
+            return;
+        }
+
+        let data = match self.checkpoint.begin_checkpoint(now_ms) {
+            Ok(data) => data,
+            Err(e) => {
+                syscall::debug(&format!("VfsService: {}", e));
+                return;
+            }
+        };
+        let key = self.checkpoint.location();
+        if self
+            .start_storage_write(&key, &data, PendingOp::CheckpointState)
+            .is_err()
+        {
+            self.checkpoint.finish_checkpoint(false);
+        }
+    }
+
+    /// Checkpoint write completed.
+    pub fn handle_checkpoint_state_result(
+        &mut self,
+        result_type: ResultKind,
+    ) -> Result<(), AppError> {
+        let ok = result_type == ResultKind::WriteOk;
+        if !ok {
+            syscall::debug(&format!(
+                "VfsService: Checkpoint write failed: {} ({})",
+                result_type as u8,
+                result_type.name()
+            ));
+        }
+        self.checkpoint.finish_checkpoint(ok);
+        Ok(())
+    }
+}
@@ -0,0 +1,706 @@
+//! Chunked content handlers for VFS Service
+//!
+//! Handles: reads of chunked files, write-at into them, and deleting chunks
+//!
+//! A file larger than one chunk is stored as a manifest under its content
+//! key and one record per chunk (see `zos_vfs::storage::chunking`). The
+//! write state machine stores new chunked files; this module covers the
+//! rest:
+//!
+//! - Reads fetch the chunks they cover one at a time, so a read-at costs
+//!   one or two chunk reads however large the file is. A hash fetches every
+//!   chunk but keeps none, feeding each to the hash as it arrives.
+//! - A write-at rewrites the chunks it touches in place, then the manifest
+//!   and inode.
+//! - Content records are deleted through `start_content_delete`, which
+//!   lets go of what the record held (a manifest's chunks, or a link's
+//!   blob) once the record itself is gone.
+//!
+//! Chunks of an owned file are sealed one by one, like whole blobs.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: a read returns exactly the bytes of the manifest it
+//!   started from
+//! - **Acceptable partial failure**: a write-at that fails part way leaves
+//!   the chunks it already rewrote, as a crash mid-write would; chunks
+//!   whose deletion fails are orphaned; a read racing a whole-file rewrite
+//!   can find its chunks gone and fails
+//! - **Forbidden**: deleting chunks a stored manifest still names
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+use sha2::{Digest, Sha256};
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError};
+use zos_ipc::storage::ResultKind;
+use zos_vfs::core::UserId;
+use zos_vfs::ipc::{vfs_msg, HashResponse, ReadFileResponse, VfsEventKind, WriteAtResponse};
+use zos_vfs::service::PermissionContext;
+use zos_vfs::storage::chunking::ChunkManifest;
+use zos_vfs::storage::integrity::ContentHash;
+use zos_vfs::{Inode, VfsError};
+
+use super::super::{
+    content_key, inode_key, parse_inode, Charge, ClientContext, ContentDeleteStage, PatchStage,
+    PendingOp, Reservation, VfsService, MAX_CONTENT_SIZE,
+};
+use super::hash::checked_content;
+use super::link::HeldContent;
+use super::storage_failure;
+
+/// A read of a chunked file in progress.
+#[derive(Clone, Debug)]
+pub struct ChunkedRead {
+    pub manifest: ChunkManifest,
+    pub reply: ChunkReadReply,
+    /// Next chunk to fetch
+    pub next: u32,
+    /// One past the last chunk to fetch
+    pub end: u32,
+    /// Bytes gathered so far
+    pub content: Vec<u8>,
+    /// Hash of the chunks fetched so far, for `ChunkReadReply::Hash`
+    pub hasher: Sha256,
+}
+
+/// Request a chunked read answers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkReadReply {
+    /// `MSG_VFS_READ`: the whole file, checked against `expected` if set
+    Read { expected: Option<ContentHash> },
+    /// `MSG_VFS_READ_AT`: a range, already capped to what the reply can
+    /// carry, placed in the shared memory of handle `shm` if set
+    ReadAt {
+        offset: u64,
+        length: u64,
+        shm: Option<u32>,
+    },
+    /// `MSG_VFS_HASH`: the hash of the whole file, checked against
+    /// `expected` if set
+    Hash { expected: Option<ContentHash> },
+}
+
+impl ChunkReadReply {
+    /// Tag of the response the read ends with.
+    pub fn response_tag(&self) -> u32 {
+        match self {
+            ChunkReadReply::Read { .. } => vfs_msg::MSG_VFS_READ_RESPONSE,
+            ChunkReadReply::ReadAt { .. } => vfs_msg::MSG_VFS_READ_AT_RESPONSE,
+            ChunkReadReply::Hash { .. } => vfs_msg::MSG_VFS_HASH_RESPONSE,
+        }
+    }
+}
+
+/// A write through a handle into a chunked file.
+#[derive(Clone, Debug)]
+pub struct ChunkPatch {
+    pub path: String,
+    pub perm_ctx: PermissionContext,
+    pub offset: u64,
+    pub data: Vec<u8>,
+    /// Manifest as it was before the write
+    pub old: ChunkManifest,
+}
+
+impl ChunkPatch {
+    /// Manifest once the write lands: the same chunks, grown if the data
+    /// ends past the old end.
+    pub fn new_manifest(&self) -> ChunkManifest {
+        let end = self.offset.saturating_add(self.data.len() as u64);
+        ChunkManifest {
+            size: self.old.size.max(end),
+            ..self.old
+        }
+    }
+
+    /// Chunks the write rewrites, in order: those the data covers, plus any
+    /// between the old end and the data, which are filled with zeros.
+    pub fn chunks(&self) -> Range<u32> {
+        let from = self.offset.min(self.old.size);
+        let end = self.offset.saturating_add(self.data.len() as u64);
+        self.new_manifest()
+            .chunks_covering(from, end.saturating_sub(from))
+    }
+}
+
+/// Fresh id for the chunks of a whole-file write.
+pub fn new_content_id() -> Result<u64, VfsError> {
+    let mut id = [0u8; 8];
+    getrandom::getrandom(&mut id)
+        .map_err(|e| VfsError::EncryptionError(format!("No randomness: {}", e)))?;
+    Ok(u64::from_le_bytes(id))
+}
+
+impl VfsService {
+    // =========================================================================
+    // Reads
+    // =========================================================================
+
+    /// Answer a read whose content record turned out to be a manifest.
+    pub fn start_chunk_read(
+        &mut self,
+        client_ctx: &ClientContext,
+        record: &[u8],
+        reply: ChunkReadReply,
+    ) -> Result<(), AppError> {
+        let manifest = match ChunkManifest::decode(record) {
+            Ok(manifest) => manifest,
+            Err(error) => return self.send_chunk_read_reply(client_ctx, reply, Err(error)),
+        };
+        let chunks = match reply {
+            ChunkReadReply::Read { .. } if manifest.size > MAX_CONTENT_SIZE as u64 => {
+                // Too large for one response; the client has to use a handle
+                return self.send_chunk_read_reply(client_ctx, reply, Err(VfsError::FileTooLarge));
+            }
+            ChunkReadReply::Read { .. } | ChunkReadReply::Hash { .. } => 0..manifest.chunk_count(),
+            ChunkReadReply::ReadAt { offset, length, .. } => {
+                manifest.chunks_covering(offset, length)
+            }
+        };
+        if chunks.is_empty() {
+            return self.send_chunk_read_reply(client_ctx, reply, Ok(Vec::new()));
+        }
+        let read = ChunkedRead {
+            manifest,
+            reply,
+            next: chunks.start,
+            end: chunks.end,
+            content: Vec::new(),
+            hasher: Sha256::new(),
+        };
+        self.start_storage_read(
+            &manifest.chunk_key(chunks.start),
+            PendingOp::ChunkReadOp {
+                ctx: client_ctx.clone(),
+                read,
+            },
+        )
+    }
+
+    /// Handle a chunk read: keep the part the read wants, fetch the next.
+    pub fn handle_chunk_read_result(
+        &mut self,
+        client_ctx: ClientContext,
+        mut read: ChunkedRead,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let index = read.next;
+        let chunk = match self.open_chunk(&read.manifest, index, result_type, data) {
+            Ok(chunk) => chunk,
+            Err(error) => return self.send_chunk_read_reply(&client_ctx, read.reply, Err(error)),
+        };
+        match read.reply {
+            ChunkReadReply::Read { .. } => read.content.extend_from_slice(&chunk),
+            ChunkReadReply::Hash { .. } => read.hasher.update(&chunk),
+            ChunkReadReply::ReadAt { offset, length, .. } => {
+                let start = read.manifest.chunk_start(index);
+                let end = offset
+                    .saturating_add(length)
+                    .min(start + chunk.len() as u64);
+                let from = offset.max(start);
+                if from < end {
+                    read.content
+                        .extend_from_slice(&chunk[(from - start) as usize..(end - start) as usize]);
+                }
+            }
+        }
+
+        read.next += 1;
+        if read.next == read.end {
+            if let ChunkReadReply::Hash { expected } = read.reply {
+                let hash = read.hasher.finalize().into();
+                return self.send_hash_reply(&client_ctx, expected, hash, read.manifest.size);
+            }
+            let content = core::mem::take(&mut read.content);
+            return self.send_chunk_read_reply(&client_ctx, read.reply, Ok(content));
+        }
+        let key = read.manifest.chunk_key(read.next);
+        self.start_storage_read(
+            &key,
+            PendingOp::ChunkReadOp {
+                ctx: client_ctx,
+                read,
+            },
+        )
+    }
+
+    /// Plaintext of chunk `index` from its storage result.
+    fn open_chunk(
+        &self,
+        manifest: &ChunkManifest,
+        index: u32,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<Vec<u8>, VfsError> {
+        match result_type {
+            ResultKind::ReadOk => {
+                let chunk = self.content_keys.open(data)?;
+                if chunk.len() != manifest.chunk_len(index) {
+                    return Err(VfsError::StorageError(format!(
+                        "Chunk {} of {:016x} has {} bytes, expected {}",
+                        index,
+                        manifest.id,
+                        chunk.len(),
+                        manifest.chunk_len(index)
+                    )));
+                }
+                Ok(chunk)
+            }
+            ResultKind::NotFound => {
+                syscall::debug(&format!(
+                    "VfsService: CORRUPTION: Chunk {} of {:016x} missing",
+                    index, manifest.id
+                ));
+                Err(VfsError::StorageError(format!(
+                    "Chunk {} missing for existing manifest",
+                    index
+                )))
+            }
+            _ => Err(VfsError::StorageError(format!(
+                "Chunk read failed: {} ({})",
+                result_type as u8,
+                result_type.name()
+            ))),
+        }
+    }
+
+    fn send_chunk_read_reply(
+        &mut self,
+        client_ctx: &ClientContext,
+        reply: ChunkReadReply,
+        result: Result<Vec<u8>, VfsError>,
+    ) -> Result<(), AppError> {
+        match reply {
+            ChunkReadReply::Read { expected } => {
+                let response = ReadFileResponse {
+                    result: result.and_then(|content| checked_content(expected.as_ref(), content)),
+                };
+                self.send_response(client_ctx, reply.response_tag(), &response)
+            }
+            ChunkReadReply::ReadAt { shm, .. } => self.send_read_at_reply(client_ctx, shm, result),
+            // Only an empty file is answered with its content rather than
+            // through the hasher
+            ChunkReadReply::Hash { expected } => match result {
+                Ok(content) => {
+                    let hash = Sha256::digest(&content).into();
+                    self.send_hash_reply(client_ctx, expected, hash, content.len() as u64)
+                }
+                Err(error) => {
+                    let response = HashResponse { result: Err(error) };
+                    self.send_response(client_ctx, reply.response_tag(), &response)
+                }
+            },
+        }
+    }
+
+    // =========================================================================
+    // Write-at
+    // =========================================================================
+
+    /// Write through a handle into a file whose content record turned out
+    /// to be a manifest.
+    pub fn start_chunk_patch(
+        &mut self,
+        client_ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        offset: u64,
+        data: Vec<u8>,
+        record: &[u8],
+    ) -> Result<(), AppError> {
+        let old = match ChunkManifest::decode(record) {
+            Ok(manifest) => manifest,
+            Err(error) => return self.send_patch_error(&client_ctx, error),
+        };
+        let key = inode_key(&path);
+        let patch = ChunkPatch {
+            path,
+            perm_ctx,
+            offset,
+            data,
+            old,
+        };
+        self.start_storage_read(
+            &key,
+            PendingOp::ChunkPatchOp {
+                ctx: client_ctx,
+                patch,
+                stage: PatchStage::ReadingInode,
+            },
+        )
+    }
+
+    /// Handle a storage result of a write-at into a chunked file.
+    pub fn handle_chunk_patch_result(
+        &mut self,
+        client_ctx: ClientContext,
+        patch: ChunkPatch,
+        stage: PatchStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        match stage {
+            PatchStage::ReadingInode => {
+                let inode = match result_type {
+                    ResultKind::ReadOk => match parse_inode(data) {
+                        Ok(inode) if inode.is_file() => inode,
+                        Ok(_) => return self.send_patch_error(&client_ctx, VfsError::NotAFile),
+                        Err(e) => {
+                            return self.send_patch_error(
+                                &client_ctx,
+                                VfsError::StorageError(format!("Failed to parse inode: {}", e)),
+                            );
+                        }
+                    },
+                    ResultKind::NotFound => {
+                        return self.send_patch_error(&client_ctx, VfsError::NotFound)
+                    }
+                    _ => {
+                        return self.send_patch_error(
+                            &client_ctx,
+                            storage_failure("Inode read", result_type),
+                        )
+                    }
+                };
+
+                let size = patch.new_manifest().size;
+                let added = patch
+                    .perm_ctx
+                    .user_id
+                    .filter(|_| size > 0)
+                    .map(|user_id| Charge {
+                        user_id,
+                        bytes: size,
+                    });
+                let reservation = match self.quotas.reserve(added, Charge::of(&inode)) {
+                    Ok(reservation) => reservation,
+                    Err(error) => return self.send_patch_error(&client_ctx, error),
+                };
+                let first = patch.chunks().start;
+                self.patch_chunk(client_ctx, patch, first, Box::new(inode), reservation)
+            }
+            PatchStage::ReadingChunk {
+                index,
+                inode,
+                reservation,
+            } => {
+                let old = match self.open_chunk(&patch.old, index, result_type, data) {
+                    Ok(old) => old,
+                    Err(error) => return self.fail_patch(&client_ctx, reservation, error),
+                };
+                self.write_patched_chunk(client_ctx, patch, index, &old, inode, reservation)
+            }
+            PatchStage::WritingChunk {
+                index,
+                inode,
+                reservation,
+            } => {
+                if result_type != ResultKind::WriteOk {
+                    let error = storage_failure("Chunk write", result_type);
+                    return self.fail_patch(&client_ctx, reservation, error);
+                }
+                self.patch_chunk(client_ctx, patch, index + 1, inode, reservation)
+            }
+            PatchStage::WritingManifest { inode, reservation } => {
+                if result_type != ResultKind::WriteOk {
+                    let error = storage_failure("Manifest write", result_type);
+                    return self.fail_patch(&client_ctx, reservation, error);
+                }
+                self.write_patched_inode(client_ctx, patch, inode, reservation)
+            }
+            PatchStage::WritingInode { size, reservation } => {
+                if result_type != ResultKind::WriteOk {
+                    let error = storage_failure("Inode write", result_type);
+                    return self.fail_patch(&client_ctx, reservation, error);
+                }
+                syscall::debug(&format!(
+                    "VfsService: write_at {} completed ({} bytes)",
+                    patch.path, size
+                ));
+                self.notify_watchers(VfsEventKind::Write, &patch.path, None);
+                let response = WriteAtResponse { result: Ok(size) };
+                self.send_response(&client_ctx, vfs_msg::MSG_VFS_WRITE_AT_RESPONSE, &response)
+            }
+        }
+    }
+
+    /// Rewrite chunk `index`, reading it first if it keeps some bytes, or
+    /// move on to the manifest once every chunk is written.
+    fn patch_chunk(
+        &mut self,
+        client_ctx: ClientContext,
+        patch: ChunkPatch,
+        index: u32,
+        inode: Box<Inode>,
+        reservation: Reservation,
+    ) -> Result<(), AppError> {
+        if index >= patch.chunks().end {
+            let new = patch.new_manifest();
+            if new == patch.old {
+                return self.write_patched_inode(client_ctx, patch, inode, reservation);
+            }
+            let started = self.start_storage_write(
+                &content_key(&patch.path),
+                &new.encode(),
+                PendingOp::ChunkPatchOp {
+                    ctx: client_ctx,
+                    patch,
+                    stage: PatchStage::WritingManifest { inode, reservation },
+                },
+            );
+            if started.is_err() {
+                self.quotas.undo(&reservation);
+            }
+            return started;
+        }
+
+        if !patch
+            .old
+            .keeps_bytes(index, patch.offset, patch.data.len() as u64)
+        {
+            return self.write_patched_chunk(client_ctx, patch, index, &[], inode, reservation);
+        }
+        let started = self.start_storage_read(
+            &patch.old.chunk_key(index),
+            PendingOp::ChunkPatchOp {
+                ctx: client_ctx,
+                patch,
+                stage: PatchStage::ReadingChunk {
+                    index,
+                    inode,
+                    reservation,
+                },
+            },
+        );
+        if started.is_err() {
+            self.quotas.undo(&reservation);
+        }
+        started
+    }
+
+    fn write_patched_chunk(
+        &mut self,
+        client_ctx: ClientContext,
+        patch: ChunkPatch,
+        index: u32,
+        old: &[u8],
+        inode: Box<Inode>,
+        reservation: Reservation,
+    ) -> Result<(), AppError> {
+        let chunk = patch
+            .new_manifest()
+            .patch_chunk(index, old, patch.offset, &patch.data);
+        let blob = match self.seal_content(patch.perm_ctx.user_id, chunk) {
+            Ok(blob) => blob,
+            Err(error) => return self.fail_patch(&client_ctx, reservation, error),
+        };
+        let started = self.start_storage_write(
+            &patch.old.chunk_key(index),
+            &blob,
+            PendingOp::ChunkPatchOp {
+                ctx: client_ctx,
+                patch,
+                stage: PatchStage::WritingChunk {
+                    index,
+                    inode,
+                    reservation,
+                },
+            },
+        );
+        if started.is_err() {
+            self.quotas.undo(&reservation);
+        }
+        started
+    }
+
+    fn write_patched_inode(
+        &mut self,
+        client_ctx: ClientContext,
+        patch: ChunkPatch,
+        mut inode: Box<Inode>,
+        reservation: Reservation,
+    ) -> Result<(), AppError> {
+        // Same ownership a whole-file write would give it
+        let size = patch.new_manifest().size;
+        inode.size = size;
+        inode.owner_id = patch.perm_ctx.user_id;
+        inode.encrypted = patch.perm_ctx.user_id.is_some();
+        inode.modified_at = syscall::get_wallclock();
+        // Rehashing would mean reading every chunk; the file goes without
+        inode.content_hash = None;
+        let inode_json = match serde_json::to_vec(&*inode) {
+            Ok(json) => json,
+            Err(e) => {
+                let error = VfsError::StorageError(format!("Failed to serialize inode: {}", e));
+                return self.fail_patch(&client_ctx, reservation, error);
+            }
+        };
+        let started = self.start_storage_write(
+            &inode_key(&patch.path),
+            &inode_json,
+            PendingOp::ChunkPatchOp {
+                ctx: client_ctx,
+                patch,
+                stage: PatchStage::WritingInode { size, reservation },
+            },
+        );
+        if started.is_err() {
+            self.quotas.undo(&reservation);
+        }
+        started
+    }
+
+    fn fail_patch(
+        &mut self,
+        client_ctx: &ClientContext,
+        reservation: Reservation,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        self.quotas.undo(&reservation);
+        self.send_patch_error(client_ctx, error)
+    }
+
+    fn send_patch_error(
+        &self,
+        client_ctx: &ClientContext,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        syscall::debug(&format!("VfsService: chunked write_at failed: {:?}", error));
+        let response = WriteAtResponse { result: Err(error) };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_WRITE_AT_RESPONSE, &response)
+    }
+
+    /// Seal `plaintext` for `user_id`; unowned content is stored as is.
+    pub(in crate::services::vfs) fn seal_content(
+        &self,
+        user_id: Option<UserId>,
+        plaintext: Vec<u8>,
+    ) -> Result<Vec<u8>, VfsError> {
+        match user_id {
+            Some(user_id) => self.content_keys.seal(user_id, &plaintext),
+            None => Ok(plaintext),
+        }
+    }
+
+    // =========================================================================
+    // Deletion
+    // =========================================================================
+
+    /// Delete a file's content record, handing the result to `then` as if
+    /// it had deleted the record itself.
+    ///
+    /// The record is read first: a manifest's chunks are deleted, and a
+    /// link's blob released, once the record is gone. A link can stand for
+    /// content of any size, so the inode's size can't rule either out.
+    pub fn start_content_delete(&mut self, path: &str, then: PendingOp) -> Result<(), AppError> {
+        self.start_storage_read(
+            &content_key(path),
+            PendingOp::ContentDeleteOp {
+                path: path.into(),
+                stage: ContentDeleteStage::ReadingRecord,
+                then: Box::new(then),
+            },
+        )
+    }
+
+    /// Handle a step of a content record delete.
+    pub fn handle_content_delete_result(
+        &mut self,
+        ctx: &AppContext,
+        path: String,
+        stage: ContentDeleteStage,
+        then: PendingOp,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        match (stage, result_type) {
+            (ContentDeleteStage::ReadingRecord, ResultKind::ReadOk) => {
+                let held = match HeldContent::of(data) {
+                    Ok(held) => held,
+                    Err(e) => {
+                        // What it held can't be found; deleting the record
+                        // still leaves nothing pointing at it
+                        syscall::debug(&format!(
+                            "VfsService: {} has an unreadable content record, orphaning what it held: {:?}",
+                            path, e
+                        ));
+                        None
+                    }
+                };
+                self.start_storage_delete(
+                    &content_key(&path),
+                    PendingOp::ContentDeleteOp {
+                        path,
+                        stage: ContentDeleteStage::DeletingRecord { held },
+                        then: Box::new(then),
+                    },
+                )
+            }
+            (ContentDeleteStage::DeletingRecord { held }, result_type) => {
+                if let (Some(held), ResultKind::WriteOk) = (held, result_type) {
+                    self.release_held(held);
+                }
+                self.dispatch_storage_result(ctx, then, result_type, &[])
+            }
+            // Nothing to delete, or the read failed: the caller handles
+            // that as it would the delete's own result
+            (ContentDeleteStage::ReadingRecord, result_type) => {
+                self.dispatch_storage_result(ctx, then, result_type, &[])
+            }
+        }
+    }
+
+    /// Delete the chunks `manifest` names, in the background.
+    ///
+    /// Call only once no content record names them.
+    pub fn start_chunk_release(&mut self, manifest: ChunkManifest) {
+        self.release_chunk(manifest, 0);
+    }
+
+    /// Handle a chunk delete: move on to the next whatever happened.
+    pub fn handle_release_chunks_result(
+        &mut self,
+        manifest: ChunkManifest,
+        index: u32,
+        result_type: ResultKind,
+    ) -> Result<(), AppError> {
+        if !matches!(result_type, ResultKind::WriteOk | ResultKind::NotFound) {
+            syscall::debug(&format!(
+                "VfsService: Chunk {} of {:016x} not deleted: {} ({}) - orphaned",
+                index,
+                manifest.id,
+                result_type as u8,
+                result_type.name()
+            ));
+        }
+        self.release_chunk(manifest, index + 1);
+        Ok(())
+    }
+
+    fn release_chunk(&mut self, manifest: ChunkManifest, index: u32) {
+        if index >= manifest.chunk_count() {
+            return;
+        }
+        // Releasing works for no client, so no request deadline applies
+        let deadline = self.request_deadline.take();
+        let started = self.start_storage_delete(
+            &manifest.chunk_key(index),
+            PendingOp::ReleaseChunks {
+                manifest,
+                next: index,
+            },
+        );
+        self.request_deadline = deadline;
+        if started.is_err() {
+            syscall::debug(&format!(
+                "VfsService: Cannot delete chunks {}.. of {:016x} - orphaned",
+                index, manifest.id
+            ));
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Request deadlines
+//!
+//! A client request may carry a deadline, after which the client has given
+//! up waiting. Storage operations started for the request remember it, and
+//! so do the operations that continue a multi-step request once a storage
+//! result arrives. Past the deadline the pending entry is dropped, and a
+//! late storage result is then ignored like any other unknown request.
+//!
+//! Storage operations already handed to the HAL cannot be recalled, so an
+//! expired write may still land; only the bookkeeping and the rest of the
+//! chain are cancelled.
+//!
+//! # Stuck operations
+//!
+//! Requests without a deadline could wait forever on a result the platform
+//! lost. A `Watchdog` timer fires every `DEFAULT_WATCHDOG_PERIOD_MS`; an
+//! operation pending at two firings in a row is aborted with
+//! `storage_cancel`, and the `Cancelled` result fails it through its usual
+//! handler, which answers the client. If the HAL no longer knows the
+//! request there is no result to wait for, and the operation is dropped.
+//!
+//! # Timeouts
+//!
+//! Whatever the watchdog manages, no operation waits longer than
+//! `OP_TIMEOUT_NS` for its result. Past that it is dropped, the HAL asked
+//! to abort it, and its client answered with a `StorageErrorKind::Timeout`
+//! error. A multi-step request restarts the clock with each step.
+//!
+//! Operations timed out, dropped past their deadline and aborted are
+//! counted, and published for the supervisor's metrics as
+//! `VFS:STATS:{hex_json}` at most once per update tick, whenever they
+//! changed.
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_vfs::{StorageErrorKind, VfsError};
+
+use super::super::{ClientContext, VfsService, OP_TIMEOUT_NS};
+use super::checkpoint::InterruptedResponse;
+
+impl VfsService {
+    /// Remember when `request_id`'s result is due, and the deadline of the
+    /// request that started it.
+    pub(in crate::services::vfs) fn track_deadline(&mut self, request_id: u32) {
+        let due = syscall::get_time().saturating_add(OP_TIMEOUT_NS);
+        self.op_timeouts.insert(request_id, due);
+        if let Some(deadline) = self.request_deadline {
+            self.op_deadlines.insert(request_id, deadline);
+        }
+    }
+
+    /// Continue the request that started `request_id` under its deadline.
+    ///
+    /// Returns false if the deadline has already passed.
+    pub(in crate::services::vfs) fn resume_deadline(
+        &mut self,
+        request_id: u32,
+        now_ns: u64,
+    ) -> bool {
+        self.op_timeouts.remove(&request_id);
+        let deadline = self.op_deadlines.remove(&request_id);
+        if deadline.is_some_and(|d| d <= now_ns) {
+            return false;
+        }
+        // Receiving the storage result cleared our deadline; messages sent
+        // from here on (the response, further requests) carry it again
+        self.request_deadline = deadline;
+        if deadline.is_some() {
+            syscall::set_deadline(deadline);
+        }
+        true
+    }
+
+    /// Drop pending operations whose request deadline has passed, and time
+    /// out those whose result is overdue.
+    pub fn expire_pending_ops(&mut self, now_ns: u64) {
+        self.time_out_pending_ops(now_ns);
+
+        let batches = self.batches.get_mut().expire(now_ns);
+        if batches > 0 {
+            syscall::debug(&format!(
+                "VfsService: Dropped {} batch(es) past their deadline",
+                batches
+            ));
+        }
+        let expired: Vec<u32> = self
+            .op_deadlines
+            .iter()
+            .filter(|(_, &deadline)| deadline <= now_ns)
+            .map(|(&request_id, _)| request_id)
+            .collect();
+        if expired.is_empty() {
+            return;
+        }
+        for request_id in &expired {
+            self.op_deadlines.remove(request_id);
+            self.op_timeouts.remove(request_id);
+            self.pending_ops.remove(request_id);
+        }
+        self.stats.past_deadline += expired.len() as u64;
+        self.stats_dirty = true;
+        syscall::debug(&format!(
+            "VfsService: Dropped {} pending operation(s) past their deadline",
+            expired.len()
+        ));
+    }
+
+    /// Watchdog timer fired: abort the operations pending since the last
+    /// firing.
+    pub fn abort_stuck_ops(&mut self) {
+        let stuck = self.watchdog.tick(self.pending_ops.keys().copied());
+        if stuck.is_empty() {
+            return;
+        }
+        let mut dropped = 0;
+        for &request_id in &stuck {
+            if let Err(e) = syscall::storage_cancel(request_id) {
+                syscall::debug(&format!(
+                    "VfsService: storage_cancel({}) failed: {}, dropping",
+                    request_id, e
+                ));
+                self.pending_ops.remove(&request_id);
+                self.op_deadlines.remove(&request_id);
+                self.op_timeouts.remove(&request_id);
+                dropped += 1;
+            }
+        }
+        self.stats.aborted += stuck.len() as u64;
+        self.stats_dirty = true;
+        syscall::debug(&format!(
+            "VfsService: Aborted {} stuck operation(s), {} dropped",
+            stuck.len(),
+            dropped
+        ));
+    }
+
+    /// Drop the operations whose result is overdue and answer their
+    /// clients with a timeout error.
+    fn time_out_pending_ops(&mut self, now_ns: u64) {
+        let overdue: Vec<u32> = self
+            .op_timeouts
+            .iter()
+            .filter(|(_, &due)| due <= now_ns)
+            .map(|(&request_id, _)| request_id)
+            .collect();
+        if overdue.is_empty() {
+            return;
+        }
+
+        // Steps of one request answer it once
+        let mut answered = BTreeSet::new();
+        let mut replies: Vec<(ClientContext, u32, String)> = Vec::new();
+        let mut timed_out = 0;
+        for request_id in &overdue {
+            self.op_timeouts.remove(request_id);
+            self.op_deadlines.remove(request_id);
+            let Some(op) = self.pending_ops.remove(request_id) else {
+                continue;
+            };
+            // Already finished or lost; either way nothing more will come
+            let _ = syscall::storage_cancel(*request_id);
+            timed_out += 1;
+            if let Some((ctx, tag)) = op.client() {
+                let slot = ctx.batch.as_ref().map(|slot| (slot.id, slot.index));
+                if answered.insert((ctx.pid, tag, slot)) {
+                    let operation = format!("storage request {}", request_id);
+                    replies.push((ctx.clone(), tag, operation));
+                }
+            }
+        }
+        if timed_out == 0 {
+            return;
+        }
+        self.stats.timed_out += timed_out;
+        self.stats_dirty = true;
+        syscall::debug(&format!(
+            "VfsService: Timed out {} pending operation(s), answering {} client request(s)",
+            timed_out,
+            replies.len()
+        ));
+
+        for (ctx, tag, operation) in replies {
+            let response = InterruptedResponse {
+                result: Err(VfsError::storage_error(StorageErrorKind::timeout(
+                    operation,
+                ))),
+            };
+            let _ = self.send_response(&ctx, tag, &response);
+        }
+    }
+
+    /// Publish the counters for the supervisor if they changed.
+    pub fn publish_stats(&mut self) {
+        if !self.stats_dirty {
+            return;
+        }
+        self.stats_dirty = false;
+        self.stats.pending_ops = self.pending_ops.len() as u32;
+        let hex: String = self
+            .stats
+            .to_json()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        syscall::debug(&format!("{}{}", zos_ipc::debug::VFS_STATS, hex));
+    }
+}
@@ -2,26 +2,43 @@
 //!
 //! Handles: rmdir, unlink operations
 //!
+//! An unlink with `trash` set moves the file to the user's trash instead
+//! (see `trash`), unless it is already in one.
+//!
 //! # Safety Properties
 //!
 //! - **Success**: content deleted (if file), inode deleted
 //! - **Acceptable partial failure**: orphan content (content exists without inode)
+//!
+//! A file's size is released from its owner's quota once its inode is
+//! deleted; an entry that stays keeps its charge.
 //! - **Forbidden**: inode deleted while content still referenced elsewhere
+//!
+//! A recursive rmdir applies the same rules to every entry, bottom-up. An
+//! entry that can't be removed keeps its ancestors, so a partial failure
+//! never leaves inodes under a missing directory; the client gets the
+//! failed paths back.
 
 use alloc::format;
-use alloc::string::String;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use zos_apps::syscall;
 use zos_apps::{AppContext, AppError, Message};
-use zos_process::storage_result;
-use zos_vfs::ipc::{vfs_msg, RmdirRequest, RmdirResponse, UnlinkRequest, UnlinkResponse};
+use zos_ipc::storage::ResultKind;
+use zos_vfs::core::is_under;
+use zos_vfs::ipc::{
+    vfs_msg, RmdirFailure, RmdirRequest, RmdirResponse, UnlinkRequest, UnlinkResponse,
+    VfsEventKind,
+};
 use zos_vfs::service::{check_write, PermissionContext};
-use zos_vfs::Inode;
+use zos_vfs::trash::is_trashed;
 use zos_vfs::VfsError;
 
 use super::super::{
-    content_key, derive_permission_context, inode_key, result_type_name, validate_path,
-    ClientContext, InodeOpType, PendingOp, UnlinkStage, VfsService,
+    content_key, inode_key, parse_inode, validate_path, Charge, ClientContext, InodeOpType,
+    PendingOp, RmdirStage, RmdirStep, RmdirWalk, UnlinkStage, VfsService,
 };
+use super::storage_failure;
 
 impl VfsService {
     // =========================================================================
@@ -30,17 +47,13 @@ impl VfsService {
 
     /// Send an rmdir error response to the client.
     fn send_rmdir_error(&self, client_ctx: &ClientContext, error: VfsError) -> Result<(), AppError> {
-        let response = RmdirResponse {
-            result: Err(error),
-        };
+        let response = RmdirResponse::new(Err(error));
         self.send_response(client_ctx, vfs_msg::MSG_VFS_RMDIR_RESPONSE, &response)
     }
 
     /// Send an rmdir error response via debug channel (when no ClientContext available).
     fn send_rmdir_error_via_debug(&self, to_pid: u32, error: VfsError) -> Result<(), AppError> {
-        let response = RmdirResponse {
-            result: Err(error),
-        };
+        let response = RmdirResponse::new(Err(error));
         self.send_response_via_debug(to_pid, vfs_msg::MSG_VFS_RMDIR_RESPONSE, &response)
     }
 
@@ -84,12 +97,21 @@ impl VfsService {
                 VfsError::InvalidPath(String::from(reason)),
             );
         }
+        if request.path == "/" {
+            return self.send_rmdir_error_via_debug(
+                msg.from_pid,
+                VfsError::InvalidPath(String::from("Cannot remove root")),
+            );
+        }
 
-        syscall::debug(&format!("VfsService: rmdir {}", request.path));
+        syscall::debug(&format!(
+            "VfsService: rmdir {} (recursive={})",
+            request.path, request.recursive
+        ));
 
         // Derive permission context from caller
-        let perm_ctx = derive_permission_context(msg.from_pid, &request.path);
-        let client_ctx = ClientContext::from_message(msg);
+        let perm_ctx = self.permission_context(msg.from_pid, &request.path);
+        let client_ctx = self.client_context(msg);
 
         // Check inode exists and is directory
         self.start_storage_read(
@@ -126,11 +148,16 @@ impl VfsService {
             );
         }
 
+        // Files already in a trash are deleted outright
+        if request.trash && !is_trashed(&request.path) {
+            return self.start_trash_move(msg, request.path);
+        }
+
         syscall::debug(&format!("VfsService: unlink {}", request.path));
 
         // Derive permission context from caller
-        let perm_ctx = derive_permission_context(msg.from_pid, &request.path);
-        let client_ctx = ClientContext::from_message(msg);
+        let perm_ctx = self.permission_context(msg.from_pid, &request.path);
+        let client_ctx = self.client_context(msg);
 
         // Start the unlink state machine: first read inode to verify it's a file
         self.start_storage_read(
@@ -154,36 +181,37 @@ impl VfsService {
         client_ctx: &ClientContext,
         path: &str,
         perm_ctx: &PermissionContext,
-        result_type: u8,
+        recursive: bool,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         // Handle result type strictly
         match result_type {
-            storage_result::READ_OK => {
+            ResultKind::ReadOk => {
                 // Good - parse and validate
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 return self.send_rmdir_error(client_ctx, VfsError::NotFound);
             }
             _ => {
                 syscall::debug(&format!(
                     "VfsService: rmdir {} inode read failed: {} ({})",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 return self.send_rmdir_error(
                     client_ctx,
                     VfsError::StorageError(format!(
                         "Inode read failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )),
                 );
             }
         }
 
-        match serde_json::from_slice::<Inode>(data) {
+        match parse_inode(data) {
             Ok(inode) if inode.is_directory() => {
                 // Check write permission before deleting
                 if !check_write(&inode, perm_ctx) {
@@ -194,12 +222,24 @@ impl VfsService {
                     return self.send_rmdir_error(client_ctx, VfsError::PermissionDenied);
                 }
 
-                self.start_storage_delete(
-                    &inode_key(path),
-                    PendingOp::DeleteInode {
-                        ctx: Some(client_ctx.clone()),
-                        response_tag: vfs_msg::MSG_VFS_RMDIR_RESPONSE,
-                    },
+                // List the children first; the directory itself goes last
+                let walk = RmdirWalk {
+                    steps: alloc::vec![
+                        RmdirStep::Remove {
+                            path: path.to_string(),
+                            is_file: false,
+                            charge: None,
+                        },
+                        RmdirStep::List(path.to_string()),
+                    ],
+                    failed: Vec::new(),
+                };
+                self.rmdir_next_step(
+                    client_ctx.clone(),
+                    path.to_string(),
+                    perm_ctx.clone(),
+                    recursive,
+                    walk,
                 )
             }
             Ok(_) => self.send_rmdir_error(client_ctx, VfsError::NotADirectory),
@@ -222,36 +262,36 @@ impl VfsService {
         client_ctx: &ClientContext,
         path: &str,
         perm_ctx: &PermissionContext,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         // Handle result type strictly
         match result_type {
-            storage_result::READ_OK => {
+            ResultKind::ReadOk => {
                 // Good - parse and validate
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 return self.send_unlink_error(client_ctx, VfsError::NotFound);
             }
             _ => {
                 syscall::debug(&format!(
                     "VfsService: unlink {} inode read failed: {} ({})",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 return self.send_unlink_error(
                     client_ctx,
                     VfsError::StorageError(format!(
                         "Inode read failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )),
                 );
             }
         }
 
-        match serde_json::from_slice::<Inode>(data) {
+        match parse_inode(data) {
             Ok(inode) if inode.is_file() => {
                 // Check write permission before deleting
                 if !check_write(&inode, perm_ctx) {
@@ -263,8 +303,8 @@ impl VfsService {
                 }
 
                 // Delete content first (intermediate step - no response)
-                if let Err(e) = self.start_storage_delete(
-                    &content_key(path),
+                if let Err(e) = self.start_content_delete(
+                    path,
                     PendingOp::DeleteContent {
                         path: path.to_string(),
                     },
@@ -276,14 +316,17 @@ impl VfsService {
                     // Continue to delete inode anyway - content may not exist
                 }
 
-                // Delete inode (this will send the response)
+                // Delete inode (this will send the response). The result
+                // doesn't come back here, so the charge is released up front
                 self.start_storage_delete(
                     &inode_key(path),
                     PendingOp::DeleteInode {
                         ctx: Some(client_ctx.clone()),
                         response_tag: vfs_msg::MSG_VFS_UNLINK_RESPONSE,
                     },
-                )
+                )?;
+                self.quotas.release(Charge::of(&inode));
+                Ok(())
             }
             Ok(_) => self.send_unlink_error(client_ctx, VfsError::NotAFile),
             Err(e) => {
@@ -306,7 +349,7 @@ impl VfsService {
         &mut self,
         client_ctx: Option<&ClientContext>,
         response_tag: u32,
-        result_type: u8,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
         // If no client context, this is an intermediate step - no response needed
         let client_ctx = match client_ctx {
@@ -314,20 +357,18 @@ impl VfsService {
             None => return Ok(()),
         };
 
-        let success = result_type == storage_result::WRITE_OK;
+        let success = result_type == ResultKind::WriteOk;
 
         if response_tag == vfs_msg::MSG_VFS_RMDIR_RESPONSE {
-            let response = RmdirResponse {
-                result: if success {
-                    Ok(())
-                } else {
-                    Err(VfsError::StorageError(format!(
-                        "Rmdir inode delete failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
-                    )))
-                },
-            };
+            let response = RmdirResponse::new(if success {
+                Ok(())
+            } else {
+                Err(VfsError::StorageError(format!(
+                    "Rmdir inode delete failed: {} ({})",
+                    result_type as u8,
+                    result_type.name()
+                )))
+            });
             self.send_response(client_ctx, response_tag, &response)
         } else if response_tag == vfs_msg::MSG_VFS_UNLINK_RESPONSE {
             let response = UnlinkResponse {
@@ -336,8 +377,8 @@ impl VfsService {
                 } else {
                     Err(VfsError::StorageError(format!(
                         "Unlink inode delete failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )))
                 },
             };
@@ -354,12 +395,227 @@ impl VfsService {
     pub fn handle_delete_content_result(
         &mut self,
         _path: &str,
-        _result_type: u8,
+        _result_type: ResultKind,
     ) -> Result<(), AppError> {
         // Content delete is part of unlink - response sent after inode delete
         Ok(())
     }
 
+    // =========================================================================
+    // Rmdir State Machine (bottom-up delete)
+    // =========================================================================
+
+    /// Handle rmdir operation result (state machine)
+    ///
+    /// Each result updates the walk, then the next step is started:
+    /// 1. ListingChildren: queue the children (an error if not recursive)
+    /// 2. ReadingEntry: check permissions, queue the entry's removal, and
+    ///    for directories queue a listing that runs before it
+    /// 3. DeletingContent: on success queue the inode delete, on failure
+    ///    keep the inode so the content stays reachable
+    /// 4. DeletingInode: record the failure, if any
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle_rmdir_op_result(
+        &mut self,
+        client_ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        recursive: bool,
+        mut walk: RmdirWalk,
+        stage: RmdirStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        match stage {
+            RmdirStage::ListingChildren { dir } => {
+                let children = match result_type {
+                    ResultKind::ListOk => match serde_json::from_slice::<Vec<String>>(data) {
+                        Ok(children) => children,
+                        Err(e) => {
+                            walk.fail(
+                                &dir,
+                                VfsError::StorageError(format!("Failed to parse child list: {}", e)),
+                            );
+                            Vec::new()
+                        }
+                    },
+                    ResultKind::NotFound => Vec::new(),
+                    _ => {
+                        walk.fail(&dir, storage_failure("Child list", result_type));
+                        Vec::new()
+                    }
+                };
+                if !children.is_empty() && !recursive {
+                    return self.send_rmdir_error(&client_ctx, VfsError::DirectoryNotEmpty);
+                }
+                walk.steps.extend(children.into_iter().map(RmdirStep::Visit));
+            }
+            RmdirStage::ReadingEntry { path: entry } => match result_type {
+                ResultKind::ReadOk => match parse_inode(data) {
+                    Ok(inode) if !check_write(&inode, &perm_ctx) => {
+                        syscall::debug(&format!(
+                            "VfsService: Permission denied for rmdir entry {} (pid={})",
+                            entry, client_ctx.pid
+                        ));
+                        walk.fail(&entry, VfsError::PermissionDenied);
+                    }
+                    Ok(inode) => {
+                        let is_directory = inode.is_directory();
+                        walk.steps.push(RmdirStep::Remove {
+                            path: entry.clone(),
+                            is_file: inode.is_file(),
+                            charge: Charge::of(&inode),
+                        });
+                        if is_directory {
+                            walk.steps.push(RmdirStep::List(entry));
+                        }
+                    }
+                    // FAIL CLOSED: an entry we can't parse is kept
+                    Err(e) => walk.fail(
+                        &entry,
+                        VfsError::StorageError(format!("Failed to parse inode: {}", e)),
+                    ),
+                },
+                // Already removed by someone else
+                ResultKind::NotFound => {}
+                _ => walk.fail(&entry, storage_failure("Inode read", result_type)),
+            },
+            RmdirStage::DeletingContent {
+                path: entry,
+                charge,
+            } => match result_type {
+                ResultKind::WriteOk | ResultKind::NotFound => walk.steps.push(RmdirStep::Remove {
+                    path: entry,
+                    is_file: false,
+                    charge,
+                }),
+                _ => walk.fail(&entry, storage_failure("Content delete", result_type)),
+            },
+            RmdirStage::DeletingInode {
+                path: entry,
+                charge,
+            } => match result_type {
+                ResultKind::WriteOk => {
+                    self.quotas.release(charge);
+                    self.notify_watchers(VfsEventKind::Delete, &entry, None)
+                }
+                ResultKind::NotFound => {}
+                _ => walk.fail(&entry, storage_failure("Inode delete", result_type)),
+            },
+        }
+
+        self.rmdir_next_step(client_ctx, path, perm_ctx, recursive, walk)
+    }
+
+    /// Start the next step of an rmdir walk, or reply once none are left.
+    fn rmdir_next_step(
+        &mut self,
+        client_ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        recursive: bool,
+        mut walk: RmdirWalk,
+    ) -> Result<(), AppError> {
+        let (key, stage) = loop {
+            let Some(step) = walk.steps.pop() else {
+                return self.finish_rmdir(&client_ctx, &path, walk.failed);
+            };
+            match step {
+                RmdirStep::Visit(entry) => {
+                    break (inode_key(&entry), RmdirStage::ReadingEntry { path: entry })
+                }
+                RmdirStep::List(dir) => break (inode_key(&dir), RmdirStage::ListingChildren { dir }),
+                // Something under this entry is still there, so keep it
+                RmdirStep::Remove { path: entry, .. }
+                    if walk.failed.iter().any(|f| is_under(&f.path, &entry)) => {}
+                RmdirStep::Remove {
+                    path: entry,
+                    is_file: true,
+                    charge,
+                } => {
+                    break (
+                        content_key(&entry),
+                        RmdirStage::DeletingContent {
+                            path: entry,
+                            charge,
+                        },
+                    )
+                }
+                RmdirStep::Remove {
+                    path: entry,
+                    is_file: false,
+                    charge,
+                } => {
+                    break (
+                        inode_key(&entry),
+                        RmdirStage::DeletingInode {
+                            path: entry,
+                            charge,
+                        },
+                    )
+                }
+            }
+        };
+
+        let (read, list) = match stage {
+            RmdirStage::ReadingEntry { .. } => (true, false),
+            RmdirStage::ListingChildren { .. } => (false, true),
+            RmdirStage::DeletingContent { .. } | RmdirStage::DeletingInode { .. } => (false, false),
+        };
+        // Content goes with whatever its record holds
+        let content = match &stage {
+            RmdirStage::DeletingContent { path, .. } => Some(path.clone()),
+            _ => None,
+        };
+        let op = PendingOp::RmdirOp {
+            ctx: client_ctx,
+            path,
+            perm_ctx,
+            recursive,
+            walk,
+            stage,
+        };
+        if read {
+            self.start_storage_read(&key, op)
+        } else if list {
+            self.start_storage_list(&key, op)
+        } else if let Some(entry) = content {
+            self.start_content_delete(&entry, op)
+        } else {
+            self.start_storage_delete(&key, op)
+        }
+    }
+
+    /// Reply to an rmdir once its walk is done.
+    fn finish_rmdir(
+        &self,
+        client_ctx: &ClientContext,
+        path: &str,
+        failed: Vec<RmdirFailure>,
+    ) -> Result<(), AppError> {
+        if failed.is_empty() {
+            syscall::debug(&format!("VfsService: rmdir {} completed successfully", path));
+            let response = RmdirResponse::new(Ok(()));
+            return self.send_response(client_ctx, vfs_msg::MSG_VFS_RMDIR_RESPONSE, &response);
+        }
+
+        syscall::debug(&format!(
+            "VfsService: rmdir {} left {} entries in place",
+            path,
+            failed.len()
+        ));
+        let error = match failed.as_slice() {
+            // Only the directory itself failed; everything under it is gone
+            [only] if only.path == path => only.error.clone(),
+            _ => VfsError::DirectoryNotEmpty,
+        };
+        let response = RmdirResponse {
+            result: Err(error),
+            failed,
+        };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_RMDIR_RESPONSE, &response)
+    }
+
     // =========================================================================
     // Unlink State Machine (Rule 5, 7: Sequential delete)
     // =========================================================================
@@ -376,18 +632,18 @@ impl VfsService {
         path: &str,
         perm_ctx: &PermissionContext,
         stage: UnlinkStage,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         match stage {
             UnlinkStage::ReadingInode => {
                 self.handle_unlink_reading_inode(client_ctx, path, perm_ctx, result_type, data)
             }
-            UnlinkStage::DeletingContent => {
-                self.handle_unlink_deleting_content(client_ctx, path, perm_ctx, result_type)
+            UnlinkStage::DeletingContent { charge } => {
+                self.handle_unlink_deleting_content(client_ctx, path, perm_ctx, charge, result_type)
             }
-            UnlinkStage::DeletingInode => {
-                self.handle_unlink_deleting_inode(client_ctx, path, result_type)
+            UnlinkStage::DeletingInode { charge } => {
+                self.handle_unlink_deleting_inode(client_ctx, path, charge, result_type)
             }
         }
     }
@@ -398,37 +654,37 @@ impl VfsService {
         client_ctx: &ClientContext,
         path: &str,
         perm_ctx: &PermissionContext,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         // Handle result type strictly
         match result_type {
-            storage_result::READ_OK => {
+            ResultKind::ReadOk => {
                 // Good - parse and validate
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 return self.send_unlink_error(client_ctx, VfsError::NotFound);
             }
             _ => {
                 syscall::debug(&format!(
                     "VfsService: unlink {} inode read failed: {} ({})",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 return self.send_unlink_error(
                     client_ctx,
                     VfsError::StorageError(format!(
                         "Inode read failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )),
                 );
             }
         }
 
         // Parse inode - FAIL CLOSED on parse error
-        let inode = match serde_json::from_slice::<Inode>(data) {
+        let inode = match parse_inode(data) {
             Ok(inode) => inode,
             Err(e) => {
                 syscall::debug(&format!(
@@ -458,13 +714,15 @@ impl VfsService {
 
         // Permission granted - delete content FIRST (sequential, not concurrent)
         // This ensures we don't have a dangling inode reference
-        self.start_storage_delete(
-            &content_key(path),
+        self.start_content_delete(
+            path,
             PendingOp::UnlinkOp {
                 ctx: client_ctx.clone(),
                 path: path.to_string(),
                 perm_ctx: perm_ctx.clone(),
-                stage: UnlinkStage::DeletingContent,
+                stage: UnlinkStage::DeletingContent {
+                    charge: Charge::of(&inode),
+                },
             },
         )
     }
@@ -475,14 +733,15 @@ impl VfsService {
         client_ctx: &ClientContext,
         path: &str,
         perm_ctx: &PermissionContext,
-        result_type: u8,
+        charge: Option<Charge>,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
         // Rule 5: Handle content delete result properly
         match result_type {
-            storage_result::WRITE_OK => {
+            ResultKind::WriteOk => {
                 // Content deleted successfully - proceed to delete inode
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 // Content was already missing - this is acceptable, proceed to delete inode
                 // (orphaned inode scenario)
                 syscall::debug(&format!(
@@ -495,15 +754,15 @@ impl VfsService {
                 syscall::debug(&format!(
                     "VfsService: unlink {} content delete failed: {} ({}) - aborting",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 return self.send_unlink_error(
                     client_ctx,
                     VfsError::StorageError(format!(
                         "Content delete failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )),
                 );
             }
@@ -516,7 +775,7 @@ impl VfsService {
                 ctx: client_ctx.clone(),
                 path: path.to_string(),
                 perm_ctx: perm_ctx.clone(),
-                stage: UnlinkStage::DeletingInode,
+                stage: UnlinkStage::DeletingInode { charge },
             },
         )
     }
@@ -526,28 +785,31 @@ impl VfsService {
         &mut self,
         client_ctx: &ClientContext,
         path: &str,
-        result_type: u8,
+        charge: Option<Charge>,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
-        if result_type != storage_result::WRITE_OK {
+        if result_type != ResultKind::WriteOk {
             // Inode delete failed - content is now orphaned but that's acceptable
             syscall::debug(&format!(
                 "VfsService: unlink {} inode delete failed: {} ({}) - content is orphaned",
                 path,
-                result_type,
-                result_type_name(result_type)
+                result_type as u8,
+                result_type.name()
             ));
             return self.send_unlink_error(
                 client_ctx,
                 VfsError::StorageError(format!(
                     "Inode delete failed: {} ({})",
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 )),
             );
         }
 
         // Both content and inode deleted successfully
         syscall::debug(&format!("VfsService: unlink {} completed successfully", path));
+        self.quotas.release(charge);
+        self.notify_watchers(VfsEventKind::Delete, path, None);
         let response = UnlinkResponse { result: Ok(()) };
         self.send_response(client_ctx, vfs_msg::MSG_VFS_UNLINK_RESPONSE, &response)
     }
@@ -0,0 +1,78 @@
+//! Graceful drain
+//!
+//! When init sends `MSG_SERVICE_DRAIN`, VfsService answers new filesystem
+//! requests with `VfsError::Retry`, stops issuing migration batches, and
+//! waits for the storage operations already in flight. In-flight writes
+//! therefore land before the restart instead of being dropped. A final
+//! checkpoint is then written and the drain is acked.
+
+use alloc::format;
+use zos_apps::syscall;
+use zos_apps::{AppError, DrainStep, Message};
+use zos_vfs::VfsError;
+
+use super::super::{PendingOp, VfsService};
+use super::checkpoint::InterruptedResponse;
+
+impl VfsService {
+    /// Handle MSG_SERVICE_DRAIN from init.
+    pub fn handle_drain(&mut self, msg: &Message) -> Result<(), AppError> {
+        if msg.from_pid != zos_process::pid::INIT {
+            syscall::debug(&format!(
+                "VfsService: Ignoring drain request from non-init PID {}",
+                msg.from_pid
+            ));
+            return Ok(());
+        }
+        if self.drain.start() {
+            syscall::debug(&format!(
+                "VfsService: Draining ({} pending operations)",
+                self.pending_ops.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Refuse a client request because the service is draining.
+    ///
+    /// Every VFS response tag is the request tag plus one.
+    pub fn refuse_while_draining(&self, msg: &Message) -> Result<(), AppError> {
+        let ctx = self.client_context(msg);
+        let response = InterruptedResponse {
+            result: Err(VfsError::retry("VFS service is restarting")),
+        };
+        self.send_response(&ctx, msg.tag + 1, &response)
+    }
+
+    /// Advance the drain: final checkpoint once idle, then ack.
+    pub fn pump_drain(&mut self, now_ms: u64) {
+        if !self.drain.is_draining() {
+            return;
+        }
+        let idle = self
+            .pending_ops
+            .values()
+            .all(|op| matches!(op, PendingOp::CheckpointState))
+            && self.content_keys.is_idle()
+            && self.blob_releases.is_empty();
+        match self.drain.poll(idle, &mut self.checkpoint, now_ms) {
+            Ok(DrainStep::Wait) => {}
+            Ok(DrainStep::Checkpoint(data)) => {
+                let key = self.checkpoint.location();
+                if self
+                    .start_storage_write(&key, &data, PendingOp::CheckpointState)
+                    .is_err()
+                {
+                    self.checkpoint.finish_checkpoint(false);
+                }
+            }
+            Ok(DrainStep::Ack) => {
+                syscall::debug("VfsService: Drained, ready for restart");
+                if let Err(e) = self.drain.ack() {
+                    syscall::debug(&format!("VfsService: {}", e));
+                }
+            }
+            Err(e) => syscall::debug(&format!("VfsService: {}", e)),
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! Content encryption for VFS Service
+//!
+//! Handles: content key loading, sealing on write, opening on read
+//!
+//! File content owned by a user is stored sealed under that user's content
+//! key (see `zos_vfs::storage::encryption`). Keys live in KeystoreService
+//! and are loaded on first use; a user without one gets a new key, stored
+//! before anything is sealed with it. Loaded keys stay cached for the life
+//! of the service.
+//!
+//! A storage result that needs a key which isn't cached yet is parked
+//! until the load finishes, then handled as if it had just arrived.
+//! Keystore responses carry no request ID, so one key is loaded at a time.
+//!
+//! Blobs say for themselves whether they are sealed, so plaintext written
+//! before encryption existed stays readable. The migration sweep seals
+//! those, and any write seals the file anyway.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: owned content is only ever written sealed
+//! - **Acceptable partial failure**: a key that can't be loaded fails the
+//!   requests waiting on it; the next request tries again
+//! - **Forbidden**: storing an owned file's content as plaintext, or
+//!   sealing under a key that was never stored
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, Message};
+use zos_ipc::keystore_svc;
+use zos_ipc::storage::ResultKind;
+use zos_vfs::client::keystore_async::{self, KeystoreError, KeystoreReadResponse};
+use zos_vfs::core::UserId;
+use zos_vfs::storage::blobs::is_link;
+use zos_vfs::storage::chunking::is_manifest;
+use zos_vfs::storage::encryption::{self, ContentKey, StoredContentKey, SEAL_RANDOM_BYTES};
+use zos_vfs::VfsError;
+
+use super::super::{PatchStage, PendingOp, SealStage, VfsService, WriteFileStage};
+use super::hash::HashStage;
+
+/// Maximum storage results parked on a key load (Rule 11: resource limits)
+pub const MAX_KEY_WAITERS: usize = 64;
+
+/// Progress of the key load in flight.
+#[derive(Default)]
+enum KeyLoad {
+    #[default]
+    Idle,
+    /// Waiting for the keystore read of this user's key
+    Reading(UserId),
+    /// No key was stored: waiting for the new one to be
+    Writing(UserId, ContentKey),
+}
+
+/// A storage result waiting for a content key.
+struct KeyWaiter {
+    user_id: UserId,
+    op: PendingOp,
+    result_type: ResultKind,
+    data: Vec<u8>,
+    deadline: Option<u64>,
+}
+
+/// Cached content keys and the loads in progress.
+#[derive(Default)]
+pub struct ContentKeys {
+    keys: BTreeMap<UserId, ContentKey>,
+    load: KeyLoad,
+    /// Parked results, oldest first
+    waiting: Vec<KeyWaiter>,
+}
+
+impl ContentKeys {
+    /// Whether `user_id`'s key is loaded.
+    pub fn has(&self, user_id: UserId) -> bool {
+        self.keys.contains_key(&user_id)
+    }
+
+    /// Whether nothing is parked and no load is in flight.
+    pub fn is_idle(&self) -> bool {
+        self.waiting.is_empty() && matches!(self.load, KeyLoad::Idle)
+    }
+
+    /// Parked operations, for checkpointing and cancellation.
+    pub fn waiting_ops(&self) -> impl Iterator<Item = &PendingOp> {
+        self.waiting.iter().map(|waiter| &waiter.op)
+    }
+
+    /// Drop parked operations `drop` selects.
+    pub fn remove_waiting(&mut self, mut drop: impl FnMut(&PendingOp) -> bool) {
+        self.waiting.retain(|waiter| !drop(&waiter.op));
+    }
+
+    /// Cache a loaded key.
+    pub fn insert(&mut self, user_id: UserId, key: ContentKey) {
+        self.keys.insert(user_id, key);
+    }
+
+    /// Decrypt `blob` if it is sealed; legacy plaintext is returned as-is.
+    pub fn open(&self, blob: &[u8]) -> Result<Vec<u8>, VfsError> {
+        if !encryption::is_sealed(blob) {
+            return Ok(blob.to_vec());
+        }
+        let user_id = encryption::sealed_for(blob)?;
+        encryption::open(self.key(user_id)?, blob)
+    }
+
+    /// Seal `plaintext` for `user_id` with fresh randomness.
+    pub fn seal(&self, user_id: UserId, plaintext: &[u8]) -> Result<Vec<u8>, VfsError> {
+        let key = self.key(user_id)?;
+        let mut random = [0u8; SEAL_RANDOM_BYTES];
+        getrandom::getrandom(&mut random)
+            .map_err(|e| VfsError::EncryptionError(format!("No randomness: {}", e)))?;
+        encryption::seal(key, user_id, plaintext, &random)
+    }
+
+    fn key(&self, user_id: UserId) -> Result<&ContentKey, VfsError> {
+        self.keys.get(&user_id).ok_or_else(|| {
+            VfsError::EncryptionError(format!("Content key for user {:x} unavailable", user_id))
+        })
+    }
+}
+
+impl PendingOp {
+    /// The user whose content key handling `result_type`/`data` needs.
+    fn content_key_user(&self, result_type: ResultKind, data: &[u8]) -> Option<UserId> {
+        match self {
+            // The blob is handled as the record the link stood in for
+            PendingOp::FollowLink { then } => then.content_key_user(result_type, data),
+            PendingOp::GetContent { .. }
+            | PendingOp::ReadAtOp { .. }
+            | PendingOp::WriteAtOp { .. }
+            | PendingOp::HashOp {
+                stage: HashStage::ReadingContent { .. },
+                ..
+            }
+            | PendingOp::ChunkReadOp { .. }
+            | PendingOp::ChunkPatchOp {
+                stage: PatchStage::ReadingChunk { .. },
+                ..
+            } if result_type == ResultKind::ReadOk && encryption::is_sealed(data) =>
+            {
+                // A damaged header is reported by the handler
+                encryption::sealed_for(data).ok()
+            }
+            PendingOp::WriteFileOp {
+                perm_ctx,
+                stage: WriteFileStage::ReadingExisting { .. },
+                ..
+            } if matches!(result_type, ResultKind::ReadOk | ResultKind::NotFound) => {
+                perm_ctx.user_id
+            }
+            // Chunks the write-at doesn't read are sealed all the same
+            PendingOp::ChunkPatchOp {
+                patch,
+                stage: PatchStage::ReadingInode,
+                ..
+            } if result_type == ResultKind::ReadOk => patch.perm_ctx.user_id,
+            PendingOp::MigrateSeal {
+                inode,
+                stage: SealStage::ReadingContent,
+                ..
+            } if result_type == ResultKind::ReadOk
+                && !encryption::is_sealed(data)
+                && !is_manifest(data)
+                && !is_link(data) =>
+            {
+                inode.owner_id
+            }
+            _ => None,
+        }
+    }
+}
+
+impl VfsService {
+    /// Park a storage result until the content key it needs is loaded.
+    ///
+    /// Returns the operation back if no key is needed, or if there is no
+    /// room to park it; its handler then runs now (and fails for want of
+    /// the key).
+    pub(in crate::services::vfs) fn park_for_content_key(
+        &mut self,
+        op: PendingOp,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Option<PendingOp> {
+        let user_id = match op.content_key_user(result_type, data) {
+            Some(user_id) if !self.content_keys.has(user_id) => user_id,
+            _ => return Some(op),
+        };
+        if self.content_keys.waiting.len() >= MAX_KEY_WAITERS {
+            syscall::debug("VfsService: Too many operations waiting for content keys");
+            return Some(op);
+        }
+        self.content_keys.waiting.push(KeyWaiter {
+            user_id,
+            op,
+            result_type,
+            data: data.to_vec(),
+            deadline: self.request_deadline,
+        });
+        if matches!(self.content_keys.load, KeyLoad::Idle) && !self.start_content_key_load(user_id)
+        {
+            return self.content_keys.waiting.pop().map(|waiter| waiter.op);
+        }
+        None
+    }
+
+    /// Handle a keystore response for the key being loaded.
+    pub fn handle_keystore_response(
+        &mut self,
+        ctx: &AppContext,
+        msg: &Message,
+    ) -> Result<(), AppError> {
+        match (core::mem::take(&mut self.content_keys.load), msg.tag) {
+            (KeyLoad::Reading(user_id), keystore_svc::MSG_KEYSTORE_READ_RESPONSE) => {
+                let response = serde_json::from_slice::<KeystoreReadResponse>(&msg.data);
+                // Only a missing key is replaced; a new one would leave
+                // everything sealed under the stored one unreadable
+                match response.map(|r| r.result) {
+                    Ok(Ok(data)) => match serde_json::from_slice::<StoredContentKey>(&data)
+                        .map_err(|e| VfsError::DecryptionError(format!("{}", e)))
+                        .and_then(|stored| ContentKey::from_stored(&stored))
+                    {
+                        Ok(key) => {
+                            self.content_keys.keys.insert(user_id, key);
+                        }
+                        Err(e) => syscall::debug(&format!(
+                            "VfsService: Stored content key for user {:x} unreadable: {:?}",
+                            user_id, e
+                        )),
+                    },
+                    Ok(Err(KeystoreError::NotFound)) => {
+                        if self.create_content_key(user_id) {
+                            return Ok(());
+                        }
+                    }
+                    Ok(Err(e)) => syscall::debug(&format!(
+                        "VfsService: Content key read for user {:x} failed: {:?}",
+                        user_id, e
+                    )),
+                    Err(e) => syscall::debug(&format!("VfsService: Bad keystore response: {}", e)),
+                }
+                self.finish_content_key_load(ctx, user_id)
+            }
+            (KeyLoad::Writing(user_id, key), keystore_svc::MSG_KEYSTORE_WRITE_RESPONSE) => {
+                match keystore_async::parse_write_response(&msg.data) {
+                    Ok(()) => {
+                        self.content_keys.keys.insert(user_id, key);
+                    }
+                    Err(e) => syscall::debug(&format!(
+                        "VfsService: Storing content key for user {:x} failed: {}",
+                        user_id, e
+                    )),
+                }
+                self.finish_content_key_load(ctx, user_id)
+            }
+            (load, tag) => {
+                syscall::debug(&format!(
+                    "VfsService: Unexpected keystore response 0x{:x}",
+                    tag
+                ));
+                self.content_keys.load = load;
+                Ok(())
+            }
+        }
+    }
+
+    /// Ask the keystore for `user_id`'s key. Returns whether it was sent.
+    fn start_content_key_load(&mut self, user_id: UserId) -> bool {
+        match keystore_async::send_read_request(&ContentKey::storage_path(user_id)) {
+            Ok(()) => {
+                self.content_keys.load = KeyLoad::Reading(user_id);
+                true
+            }
+            Err(e) => {
+                syscall::debug(&format!(
+                    "VfsService: Cannot read content key for user {:x}: {:?}",
+                    user_id, e
+                ));
+                false
+            }
+        }
+    }
+
+    /// No key stored yet: generate one and store it. Returns whether the
+    /// write was sent.
+    fn create_content_key(&mut self, user_id: UserId) -> bool {
+        let mut bytes = [0u8; 32];
+        if let Err(e) = getrandom::getrandom(&mut bytes) {
+            syscall::debug(&format!("VfsService: Content key generation failed: {}", e));
+            return false;
+        }
+        let key = ContentKey::from_bytes(bytes);
+        bytes.fill(0);
+        let json = match serde_json::to_vec(&key.to_stored(syscall::get_wallclock())) {
+            Ok(json) => json,
+            Err(e) => {
+                syscall::debug(&format!(
+                    "VfsService: Content key serialization failed: {}",
+                    e
+                ));
+                return false;
+            }
+        };
+        match keystore_async::send_write_request(&ContentKey::storage_path(user_id), &json) {
+            Ok(()) => {
+                syscall::debug(&format!(
+                    "VfsService: Created content key for user {:x}",
+                    user_id
+                ));
+                self.content_keys.load = KeyLoad::Writing(user_id, key);
+                true
+            }
+            Err(e) => {
+                syscall::debug(&format!("VfsService: Cannot store content key: {:?}", e));
+                false
+            }
+        }
+    }
+
+    /// Replay the results parked on `user_id`'s key, then start the next
+    /// load. Users whose load can't even be requested are failed in turn.
+    fn finish_content_key_load(
+        &mut self,
+        ctx: &AppContext,
+        user_id: UserId,
+    ) -> Result<(), AppError> {
+        let mut done = user_id;
+        loop {
+            let (ready, waiting): (Vec<KeyWaiter>, Vec<KeyWaiter>) =
+                core::mem::take(&mut self.content_keys.waiting)
+                    .into_iter()
+                    .partition(|waiter| waiter.user_id == done);
+            self.content_keys.waiting = waiting;
+            let next = self
+                .content_keys
+                .waiting
+                .first()
+                .map(|waiter| waiter.user_id);
+            let stalled = next.filter(|&next| !self.start_content_key_load(next));
+
+            for waiter in ready {
+                self.replay_parked(ctx, waiter);
+            }
+            match stalled {
+                Some(next) => done = next,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    fn replay_parked(&mut self, ctx: &AppContext, waiter: KeyWaiter) {
+        if waiter.deadline.is_some_and(|d| d <= ctx.uptime_ns) {
+            return;
+        }
+        self.request_deadline = waiter.deadline;
+        if waiter.deadline.is_some() {
+            syscall::set_deadline(waiter.deadline);
+        }
+        if let Err(e) =
+            self.dispatch_storage_result(ctx, waiter.op, waiter.result_type, &waiter.data)
+        {
+            syscall::debug(&format!(
+                "VfsService: Parked storage result failed: {:?}",
+                e
+            ));
+        }
+    }
+}
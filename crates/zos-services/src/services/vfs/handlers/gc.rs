@@ -0,0 +1,487 @@
+//! Garbage collection of orphaned content
+//!
+//! Several failures leave records behind rather than risk losing data: a
+//! write whose inode never lands, a rename or purge that stops between
+//! content and inode, a blob release that stops between its count and its
+//! blob. Every `GC_INTERVAL_MS` a background sweep finds these and deletes
+//! them:
+//!
+//! - **Content records without an inode**: every `content:` key is listed,
+//!   and a record whose path has no inode is deleted, releasing the chunks
+//!   or blob reference it held.
+//! - **Blobs without a count**: every `blob:` key is listed, and a blob
+//!   whose count record is missing (or zero) is deleted with the chunks its
+//!   manifest names. A release deletes the count before the blob, so a blob
+//!   without one is never live.
+//!
+//! Chunk records are reclaimed with the record naming them; the sweep does
+//! not list them itself.
+//!
+//! The sweep runs in slices, one per update: a slice starts at most
+//! `GC_CONCURRENCY` checks and stops once it has run for
+//! `GC_SLICE_BUDGET_NS`, and is skipped while client requests keep
+//! `GC_BUSY_OPS` storage operations pending. What it reclaims is logged
+//! when it finishes and counted in the published `VfsStats`.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: every orphaned content record and uncounted blob found is
+//!   deleted, and its bytes reported
+//! - **Acceptable partial failure**: an orphan found while a client request
+//!   might still claim it is left for the next sweep; a platform that can't
+//!   list raw key prefixes has nothing collected
+//! - **Forbidden**: deleting a content record whose inode exists, or a blob
+//!   that a count or an operation in flight refers to
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::AppError;
+use zos_ipc::storage::ResultKind;
+use zos_vfs::storage::blobs::{
+    blob_key, blob_of_key, decode_refcount, refcount_key, to_hex, BlobHash, BLOB_KEY_PREFIX,
+};
+use zos_vfs::storage::chunking::{is_manifest, ChunkManifest};
+
+use super::super::{content_key, inode_key, PendingOp, VfsService, MAX_PENDING_OPS};
+use super::link::HeldContent;
+
+/// Uptime at which the first collection runs, leaving boot alone.
+pub const GC_FIRST_RUN_MS: u64 = 10 * 60 * 1000;
+
+/// Interval between collections (24 hours).
+pub const GC_INTERVAL_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Maximum collection storage operations in flight at once.
+pub const GC_CONCURRENCY: usize = 2;
+
+/// Longest a slice spends starting checks (2 ms).
+pub const GC_SLICE_BUDGET_NS: u64 = 2_000_000;
+
+/// Pending storage operations at which slices are skipped, leaving the
+/// storage HAL to client requests.
+pub const GC_BUSY_OPS: usize = MAX_PENDING_OPS / 8;
+
+/// Prefix of every content record's storage key.
+const CONTENT_KEY_PREFIX: &str = "content:";
+
+/// Something the sweep is yet to check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GcItem {
+    /// The content record of a path
+    Content(String),
+    /// A blob
+    Blob(BlobHash),
+}
+
+/// Stages of collecting one item.
+#[derive(Clone, Debug)]
+pub enum GcStage {
+    /// Listing every content record
+    ListingContent,
+    /// Listing every blob
+    ListingBlobs,
+    /// Checking the path of a content record has an inode
+    CheckingInode { path: String },
+    /// Reading an orphaned content record, to size it and find what it holds
+    ReadingContent { path: String },
+    /// Deleting an orphaned content record
+    DeletingContent {
+        path: String,
+        bytes: u64,
+        held: Option<HeldContent>,
+    },
+    /// Reading a blob's count
+    ReadingCount { hash: BlobHash },
+    /// Reading an uncounted blob, to size it and find the chunks it names
+    ReadingBlob { hash: BlobHash },
+    /// Deleting an uncounted blob's zero count, if it has one
+    DeletingCount {
+        hash: BlobHash,
+        bytes: u64,
+        manifest: Option<ChunkManifest>,
+    },
+    /// Deleting an uncounted blob
+    DeletingBlob {
+        hash: BlobHash,
+        bytes: u64,
+        manifest: Option<ChunkManifest>,
+    },
+}
+
+impl GcStage {
+    /// Blob whose records this stage reads or deletes.
+    pub fn blob(&self) -> Option<&BlobHash> {
+        match self {
+            GcStage::ReadingCount { hash }
+            | GcStage::ReadingBlob { hash }
+            | GcStage::DeletingCount { hash, .. }
+            | GcStage::DeletingBlob { hash, .. } => Some(hash),
+            _ => None,
+        }
+    }
+}
+
+/// State of the background collection.
+#[derive(Default)]
+pub struct GcSweep {
+    /// Uptime (ms) the next collection is due at, set on the first update
+    next_run_ms: Option<u64>,
+    /// Whether a collection is running
+    running: bool,
+    /// Content paths and blobs yet to check
+    queue: Vec<GcItem>,
+    /// Items checked
+    scanned: u32,
+    /// Orphans deleted
+    reclaimed: u32,
+    /// Bytes they held
+    reclaimed_bytes: u64,
+    /// Orphans left for the next collection (busy)
+    deferred: u32,
+    /// Checks that failed
+    failed: u32,
+}
+
+impl GcSweep {
+    /// Whether a collection is running.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Content paths and blobs yet to check, last first.
+    pub fn queued(&self) -> &[GcItem] {
+        &self.queue
+    }
+}
+
+impl VfsService {
+    /// Start a collection when one is due, or run a slice of the one
+    /// running.
+    pub fn pump_gc(&mut self, now_ms: u64) {
+        if self.drain.is_draining() {
+            return;
+        }
+        if self.gc.running {
+            self.run_gc_slice();
+            return;
+        }
+        let due = *self.gc.next_run_ms.get_or_insert(now_ms + GC_FIRST_RUN_MS);
+        if now_ms >= due {
+            self.start_gc(now_ms);
+        }
+    }
+
+    /// Start a collection by listing content records and blobs.
+    pub fn start_gc(&mut self, now_ms: u64) {
+        self.gc = GcSweep {
+            next_run_ms: Some(now_ms + GC_INTERVAL_MS),
+            running: true,
+            ..GcSweep::default()
+        };
+        syscall::debug("VfsService: Garbage collection started");
+        for (prefix, stage) in [
+            (CONTENT_KEY_PREFIX, GcStage::ListingContent),
+            (BLOB_KEY_PREFIX, GcStage::ListingBlobs),
+        ] {
+            if self
+                .start_storage_list(prefix, PendingOp::GcOp { stage })
+                .is_err()
+            {
+                self.gc.failed += 1;
+            }
+        }
+        self.finish_gc_if_done();
+    }
+
+    /// Collection storage operations in flight (dropped ones included).
+    fn gc_in_flight(&self) -> usize {
+        self.pending_ops
+            .values()
+            .filter(|op| matches!(op, PendingOp::GcOp { .. }))
+            .count()
+    }
+
+    /// Start queued checks, within the slice's concurrency and time budget.
+    fn run_gc_slice(&mut self) {
+        if self.pending_ops.len() >= GC_BUSY_OPS {
+            return;
+        }
+        let started = syscall::get_time();
+        let mut in_flight = self.gc_in_flight();
+        while in_flight < GC_CONCURRENCY
+            && syscall::get_time().saturating_sub(started) < GC_SLICE_BUDGET_NS
+        {
+            let Some(item) = self.gc.queue.pop() else {
+                break;
+            };
+            let result = match &item {
+                GcItem::Content(path) => self.start_storage_exists(
+                    &inode_key(path),
+                    PendingOp::GcOp {
+                        stage: GcStage::CheckingInode { path: path.clone() },
+                    },
+                ),
+                GcItem::Blob(hash) if self.blob_in_use(hash) => {
+                    self.gc.deferred += 1;
+                    continue;
+                }
+                GcItem::Blob(hash) => self.start_storage_read(
+                    &refcount_key(hash),
+                    PendingOp::GcOp {
+                        stage: GcStage::ReadingCount { hash: *hash },
+                    },
+                ),
+            };
+            if result.is_err() {
+                // Too busy - retry on the next slice
+                self.gc.queue.push(item);
+                break;
+            }
+            self.gc.scanned += 1;
+            in_flight += 1;
+        }
+        self.finish_gc_if_done();
+    }
+
+    /// Whether a client request might still write a content record whose
+    /// inode is missing now.
+    fn content_in_flux(&self) -> bool {
+        self.has_inode_mutation_in_flight()
+            || self
+                .pending_ops
+                .values()
+                .any(|op| matches!(op, PendingOp::SnapshotOp { .. }))
+    }
+
+    /// Handle a step of the collection.
+    pub fn handle_gc_result(
+        &mut self,
+        stage: GcStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let next = match (stage, result_type) {
+            (GcStage::ListingContent, ResultKind::ListOk) => {
+                let paths = list_keys(data)
+                    .iter()
+                    .filter_map(|key| key.strip_prefix(CONTENT_KEY_PREFIX))
+                    .map(|path| GcItem::Content(path.to_string()))
+                    .collect::<Vec<_>>();
+                self.gc.queue.extend(paths);
+                None
+            }
+            (GcStage::ListingBlobs, ResultKind::ListOk) => {
+                let blobs = list_keys(data)
+                    .iter()
+                    .filter_map(|key| blob_of_key(key))
+                    .map(GcItem::Blob)
+                    .collect::<Vec<_>>();
+                self.gc.queue.extend(blobs);
+                None
+            }
+            (GcStage::CheckingInode { path }, ResultKind::ExistsOk | ResultKind::NotFound) => {
+                let exists = result_type == ResultKind::ExistsOk && data.first() == Some(&1);
+                if exists {
+                    None
+                } else if self.content_in_flux() {
+                    self.gc.deferred += 1;
+                    None
+                } else {
+                    Some(GcStage::ReadingContent { path })
+                }
+            }
+            (GcStage::ReadingContent { path }, ResultKind::ReadOk) => {
+                match HeldContent::of(data) {
+                    // A record too damaged to tell what it holds is left
+                    // alone; deleting it could orphan chunks for good
+                    Err(e) => {
+                        self.gc.failed += 1;
+                        syscall::debug(&format!(
+                            "VfsService: GC left unreadable orphan {}: {:?}",
+                            path, e
+                        ));
+                        None
+                    }
+                    Ok(_) if self.content_in_flux() => {
+                        self.gc.deferred += 1;
+                        None
+                    }
+                    Ok(held) => {
+                        let chunked = match held {
+                            Some(HeldContent::Chunks(manifest)) => manifest.size,
+                            _ => 0,
+                        };
+                        Some(GcStage::DeletingContent {
+                            path,
+                            bytes: data.len() as u64 + chunked,
+                            held,
+                        })
+                    }
+                }
+            }
+            (GcStage::DeletingContent { path, bytes, held }, ResultKind::WriteOk) => {
+                syscall::debug(&format!(
+                    "VfsService: GC deleted orphaned content of {} ({} bytes)",
+                    path, bytes
+                ));
+                self.count_reclaimed(bytes);
+                if let Some(held) = held {
+                    self.release_held(held);
+                }
+                None
+            }
+            (GcStage::ReadingCount { hash }, ResultKind::ReadOk) => match decode_refcount(data) {
+                Ok(0) if !self.blob_in_use(&hash) => Some(GcStage::ReadingBlob { hash }),
+                Ok(0) => {
+                    self.gc.deferred += 1;
+                    None
+                }
+                Ok(_) => None,
+                Err(e) => {
+                    self.gc.failed += 1;
+                    syscall::debug(&format!(
+                        "VfsService: GC left blob {} with a damaged count: {:?}",
+                        to_hex(&hash),
+                        e
+                    ));
+                    None
+                }
+            },
+            (GcStage::ReadingCount { hash }, ResultKind::NotFound) => {
+                if self.blob_in_use(&hash) {
+                    self.gc.deferred += 1;
+                    None
+                } else {
+                    Some(GcStage::ReadingBlob { hash })
+                }
+            }
+            (GcStage::ReadingBlob { hash }, ResultKind::ReadOk) => {
+                let manifest = is_manifest(data)
+                    .then(|| ChunkManifest::decode(data).ok())
+                    .flatten();
+                if self.blob_in_use(&hash) {
+                    self.gc.deferred += 1;
+                    None
+                } else {
+                    Some(GcStage::DeletingCount {
+                        hash,
+                        bytes: data.len() as u64 + manifest.map_or(0, |m| m.size),
+                        manifest,
+                    })
+                }
+            }
+            (
+                GcStage::DeletingCount {
+                    hash,
+                    bytes,
+                    manifest,
+                },
+                ResultKind::WriteOk | ResultKind::NotFound,
+            ) => Some(GcStage::DeletingBlob {
+                hash,
+                bytes,
+                manifest,
+            }),
+            (
+                GcStage::DeletingBlob {
+                    hash,
+                    bytes,
+                    manifest,
+                },
+                ResultKind::WriteOk,
+            ) => {
+                syscall::debug(&format!(
+                    "VfsService: GC deleted uncounted blob {} ({} bytes)",
+                    to_hex(&hash),
+                    bytes
+                ));
+                self.count_reclaimed(bytes);
+                if let Some(manifest) = manifest {
+                    self.start_chunk_release(manifest);
+                }
+                None
+            }
+            // Gone already: someone else deleted it
+            (
+                GcStage::ReadingContent { .. }
+                | GcStage::DeletingContent { .. }
+                | GcStage::ReadingBlob { .. }
+                | GcStage::DeletingBlob { .. },
+                ResultKind::NotFound,
+            ) => None,
+            (stage, result_type) => {
+                self.gc.failed += 1;
+                syscall::debug(&format!(
+                    "VfsService: GC step {:?} failed: {} ({})",
+                    stage,
+                    result_type as u8,
+                    result_type.name()
+                ));
+                None
+            }
+        };
+
+        if let Some(stage) = next {
+            if self.start_gc_step(stage).is_err() {
+                self.gc.deferred += 1;
+            }
+        }
+        self.finish_gc_if_done();
+        Ok(())
+    }
+
+    /// Issue the storage operation of `stage`.
+    fn start_gc_step(&mut self, stage: GcStage) -> Result<(), AppError> {
+        let key = match &stage {
+            GcStage::ReadingContent { path } | GcStage::DeletingContent { path, .. } => {
+                content_key(path)
+            }
+            GcStage::ReadingBlob { hash } | GcStage::DeletingBlob { hash, .. } => blob_key(hash),
+            GcStage::DeletingCount { hash, .. } => refcount_key(hash),
+            GcStage::ListingContent
+            | GcStage::ListingBlobs
+            | GcStage::CheckingInode { .. }
+            | GcStage::ReadingCount { .. } => {
+                return Err(AppError::IpcError("Not a follow-up GC step".into()))
+            }
+        };
+        let reading = matches!(
+            stage,
+            GcStage::ReadingContent { .. } | GcStage::ReadingBlob { .. }
+        );
+        let op = PendingOp::GcOp { stage };
+        if reading {
+            self.start_storage_read(&key, op)
+        } else {
+            self.start_storage_delete(&key, op)
+        }
+    }
+
+    fn count_reclaimed(&mut self, bytes: u64) {
+        self.gc.reclaimed += 1;
+        self.gc.reclaimed_bytes += bytes;
+        self.stats.reclaimed_records += 1;
+        self.stats.reclaimed_bytes += bytes;
+        self.stats_dirty = true;
+    }
+
+    /// End the collection once nothing is queued or in flight.
+    fn finish_gc_if_done(&mut self) {
+        if !self.gc.running || !self.gc.queue.is_empty() || self.gc_in_flight() > 0 {
+            return;
+        }
+        let gc = &mut self.gc;
+        gc.running = false;
+        syscall::debug(&format!(
+            "VfsService: Garbage collection done (scanned={}, reclaimed={}, bytes={}, deferred={}, failed={})",
+            gc.scanned, gc.reclaimed, gc.reclaimed_bytes, gc.deferred, gc.failed
+        ));
+    }
+}
+
+/// Keys in a `ListOk` result; none if it is unreadable.
+fn list_keys(data: &[u8]) -> Vec<String> {
+    serde_json::from_slice(data).unwrap_or_default()
+}
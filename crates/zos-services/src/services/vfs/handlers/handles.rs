@@ -0,0 +1,643 @@
+//! File handle handlers for VFS Service
+//!
+//! Handles: open, read-at, write-at, attach-shm, close operations
+//!
+//! Handles are per-process: a handle number is only meaningful to the
+//! process that opened it, and lookups are keyed by the sender's PID.
+//! Permissions are checked once at open time. A file stored as a single
+//! blob is read whole and sliced for a read-at, and a write-at splices into
+//! it and commits it through the regular write state machine (which seals
+//! it again if it has an owner, and chunks it once it outgrows one record).
+//! A chunked file is read and patched a chunk at a time instead (see
+//! `chunks`), which is what lets a file grow past what fits in memory.
+//!
+//! A client can attach a shared memory region to a handle by sending its
+//! Memory capability. Read-at and write-at can then move the bytes through
+//! the start of the region instead of the message, up to the region's size
+//! rather than `MAX_READ_AT_LEN`. The region stays mapped until the handle
+//! is closed or another region is attached.
+//!
+//! Handles are not checkpointed; clients must reopen after a VFS restart.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: handle resolved for the calling process, range applied
+//! - **Acceptable partial failure**: same as write (orphan content)
+//! - **Forbidden**: Using a handle opened by another process, writing
+//!   through a read-only handle
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, Message};
+use zos_ipc::storage::ResultKind;
+use zos_ipc::syscall_error;
+use zos_process::SharedMemory;
+use zos_vfs::ipc::{
+    vfs_msg, AttachShmRequest, AttachShmResponse, CloseRequest, CloseResponse, OpenRequest,
+    OpenResponse, ReadAtRequest, ReadAtResponse, WriteAtRequest, WriteAtResponse,
+};
+use zos_vfs::service::{check_read, check_write, PermissionContext};
+use zos_vfs::storage::chunking::is_manifest;
+use zos_vfs::{parent_path, VfsError};
+
+use super::super::{
+    content_key, inode_key, parse_inode, validate_path, ClientContext, PendingOp, VfsService,
+    WriteFileStage, WriteReply, MAX_CHUNKED_SIZE, MAX_CONTENT_SIZE,
+};
+use super::chunks::ChunkReadReply;
+
+/// Maximum open handles per process (Rule 11: resource limits)
+pub const MAX_HANDLES_PER_PROCESS: usize = 64;
+
+/// Maximum open handles across all processes (Rule 11: resource limits)
+pub const MAX_OPEN_HANDLES: usize = 4096;
+
+/// Maximum bytes returned by one read-at.
+///
+/// Bytes are JSON-encoded as numbers, so a full chunk has to stay well
+/// inside a single IPC message.
+pub const MAX_READ_AT_LEN: u64 = 2048;
+
+/// Largest shared memory region mapped for a handle (the kernel's limit).
+pub const MAX_SHM_ATTACH_SIZE: u32 = 1 << 20;
+
+/// An open file.
+#[derive(Clone, Debug)]
+pub struct OpenFile {
+    /// Path the handle refers to
+    pub path: String,
+    /// Whether write-at is allowed
+    pub writable: bool,
+    /// Permission context of the opener, reused for writes
+    pub perm_ctx: PermissionContext,
+}
+
+/// Open handles, keyed by (pid, handle).
+#[derive(Default)]
+pub struct HandleTable {
+    open: BTreeMap<(u32, u32), OpenFile>,
+    /// Shared memory attached to open handles
+    shm: BTreeMap<(u32, u32), SharedMemory>,
+    next_handle: u32,
+}
+
+impl HandleTable {
+    /// Allocate a handle for `pid`, or `None` if a limit is reached.
+    pub fn open(&mut self, pid: u32, file: OpenFile) -> Option<u32> {
+        if self.open.len() >= MAX_OPEN_HANDLES || self.count_for(pid) >= MAX_HANDLES_PER_PROCESS {
+            return None;
+        }
+        // Handle 0 is never issued so a zeroed request can't hit a real file
+        self.next_handle = self.next_handle.wrapping_add(1).max(1);
+        while self.open.contains_key(&(pid, self.next_handle)) {
+            self.next_handle = self.next_handle.wrapping_add(1).max(1);
+        }
+        self.open.insert((pid, self.next_handle), file);
+        Some(self.next_handle)
+    }
+
+    /// Look up a handle opened by `pid`.
+    pub fn get(&self, pid: u32, handle: u32) -> Option<&OpenFile> {
+        self.open.get(&(pid, handle))
+    }
+
+    /// Close a handle opened by `pid`, unmapping its shared memory.
+    pub fn close(&mut self, pid: u32, handle: u32) -> Option<OpenFile> {
+        self.shm.remove(&(pid, handle));
+        self.open.remove(&(pid, handle))
+    }
+
+    /// Attach `region` to an open handle, replacing any region attached
+    /// before.
+    pub fn attach_shm(&mut self, pid: u32, handle: u32, region: SharedMemory) {
+        if self.open.contains_key(&(pid, handle)) {
+            self.shm.insert((pid, handle), region);
+        }
+    }
+
+    /// The shared memory attached to a handle of `pid`.
+    pub fn shm_mut(&mut self, pid: u32, handle: u32) -> Option<&mut SharedMemory> {
+        self.shm.get_mut(&(pid, handle))
+    }
+
+    /// Number of handles `pid` has open.
+    pub fn count_for(&self, pid: u32) -> usize {
+        self.open.range((pid, 0)..=(pid, u32::MAX)).count()
+    }
+}
+
+/// The part of `content` a read of `length` bytes at `offset` returns.
+pub fn read_range(content: &[u8], offset: u64, length: u64) -> &[u8] {
+    slice_range(content, offset, length.min(MAX_READ_AT_LEN))
+}
+
+/// The part of `content` in `length` bytes at `offset`.
+fn slice_range(content: &[u8], offset: u64, length: u64) -> &[u8] {
+    let start = (offset.min(content.len() as u64)) as usize;
+    let end = (start as u64)
+        .saturating_add(length)
+        .min(content.len() as u64) as usize;
+    &content[start..end]
+}
+
+/// `content` with `data` written at `offset`, zero-filling any gap.
+pub fn splice_at(content: &[u8], offset: u64, data: &[u8]) -> Vec<u8> {
+    let offset = offset as usize;
+    let mut out = content.to_vec();
+    if out.len() < offset + data.len() {
+        out.resize(offset + data.len(), 0);
+    }
+    out[offset..offset + data.len()].copy_from_slice(data);
+    out
+}
+
+impl VfsService {
+    // =========================================================================
+    // Request handlers (start async operations)
+    // =========================================================================
+
+    /// Handle MSG_VFS_OPEN - open a file handle
+    pub fn handle_open(&mut self, _ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
+        let request: OpenRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(e) => {
+                let response = OpenResponse {
+                    result: Err(VfsError::InvalidRequest(format!(
+                        "Failed to parse request: {}",
+                        e
+                    ))),
+                };
+                return self.send_response_via_debug(
+                    msg.from_pid,
+                    vfs_msg::MSG_VFS_OPEN_RESPONSE,
+                    &response,
+                );
+            }
+        };
+
+        let invalid = if let Err(reason) = validate_path(&request.path) {
+            Some(VfsError::InvalidPath(String::from(reason)))
+        } else if request.create && !request.write {
+            Some(VfsError::InvalidRequest("create requires write".into()))
+        } else {
+            None
+        };
+        if let Some(error) = invalid {
+            let response = OpenResponse { result: Err(error) };
+            return self.send_response_via_debug(
+                msg.from_pid,
+                vfs_msg::MSG_VFS_OPEN_RESPONSE,
+                &response,
+            );
+        }
+
+        syscall::debug(&format!(
+            "VfsService: open {} (write={}, create={})",
+            request.path, request.write, request.create
+        ));
+
+        let perm_ctx = self.permission_context(msg.from_pid, &request.path);
+        let client_ctx = self.client_context(msg);
+
+        self.start_storage_read(
+            &inode_key(&request.path),
+            PendingOp::OpenFileOp {
+                ctx: client_ctx,
+                path: request.path,
+                perm_ctx,
+                writable: request.write,
+                create: request.create,
+            },
+        )
+    }
+
+    /// Handle MSG_VFS_READ_AT - read a range through a handle
+    pub fn handle_read_at(&mut self, _ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let request: ReadAtRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(e) => {
+                return self.send_read_at_error(
+                    &client_ctx,
+                    VfsError::InvalidRequest(format!("Failed to parse request: {}", e)),
+                );
+            }
+        };
+
+        let path = match self.handles.get(msg.from_pid, request.handle) {
+            Some(file) => file.path.clone(),
+            None => return self.send_read_at_error(&client_ctx, unknown_handle(request.handle)),
+        };
+        // The reply carries at most MAX_READ_AT_LEN bytes, the region its size
+        let (shm, limit) = if request.shm {
+            match self.handles.shm_mut(msg.from_pid, request.handle) {
+                Some(region) => (Some(request.handle), region.len() as u64),
+                None => return self.send_read_at_error(&client_ctx, no_shm(request.handle)),
+            }
+        } else {
+            (None, MAX_READ_AT_LEN)
+        };
+
+        self.start_storage_read(
+            &content_key(&path),
+            PendingOp::ReadAtOp {
+                ctx: client_ctx,
+                offset: request.offset,
+                length: request.length.min(limit),
+                shm,
+            },
+        )
+    }
+
+    /// Handle MSG_VFS_WRITE_AT - write a range through a handle
+    pub fn handle_write_at(&mut self, _ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let request: WriteAtRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(e) => {
+                return self.send_write_at_error(
+                    &client_ctx,
+                    VfsError::InvalidRequest(format!("Failed to parse request: {}", e)),
+                );
+            }
+        };
+
+        let file = match self.handles.get(msg.from_pid, request.handle) {
+            Some(file) => file.clone(),
+            None => return self.send_write_at_error(&client_ctx, unknown_handle(request.handle)),
+        };
+        if !file.writable {
+            return self.send_write_at_error(&client_ctx, VfsError::PermissionDenied);
+        }
+        let data = match request.shm_len {
+            Some(len) => match self.fetch_shm(msg.from_pid, request.handle, len) {
+                Ok(data) => data,
+                Err(error) => return self.send_write_at_error(&client_ctx, error),
+            },
+            None => request.data,
+        };
+
+        // Rule 11: Enforce file size limit on the resulting file
+        let end = request.offset.saturating_add(data.len() as u64);
+        if end > MAX_CHUNKED_SIZE {
+            return self.send_write_at_error(&client_ctx, VfsError::FileTooLarge);
+        }
+
+        syscall::debug(&format!(
+            "VfsService: write_at {} ({} bytes at {})",
+            file.path,
+            data.len(),
+            request.offset
+        ));
+
+        self.start_storage_read(
+            &content_key(&file.path),
+            PendingOp::WriteAtOp {
+                ctx: client_ctx,
+                path: file.path,
+                perm_ctx: file.perm_ctx,
+                offset: request.offset,
+                data,
+            },
+        )
+    }
+
+    /// Handle MSG_VFS_ATTACH_SHM - map a client's region for a handle
+    pub fn handle_attach_shm(&mut self, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let result = self.attach_shm(msg);
+        // The mapping outlives the capability; dropping it lets the region
+        // go once the client and the handle are both done with it
+        for &slot in &msg.cap_slots {
+            let _ = syscall::cap_delete(slot);
+        }
+        let response = AttachShmResponse { result };
+        self.send_response(&client_ctx, vfs_msg::MSG_VFS_ATTACH_SHM_RESPONSE, &response)
+    }
+
+    /// Handle MSG_VFS_CLOSE - close a handle
+    pub fn handle_close(&mut self, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let result = match serde_json::from_slice::<CloseRequest>(&msg.data) {
+            Ok(request) => match self.handles.close(msg.from_pid, request.handle) {
+                Some(_) => Ok(()),
+                None => Err(unknown_handle(request.handle)),
+            },
+            Err(e) => Err(VfsError::InvalidRequest(format!(
+                "Failed to parse request: {}",
+                e
+            ))),
+        };
+        let response = CloseResponse { result };
+        self.send_response(&client_ctx, vfs_msg::MSG_VFS_CLOSE_RESPONSE, &response)
+    }
+
+    fn attach_shm(&mut self, msg: &Message) -> Result<u32, VfsError> {
+        let request: AttachShmRequest = serde_json::from_slice(&msg.data)
+            .map_err(|e| VfsError::InvalidRequest(format!("Failed to parse request: {}", e)))?;
+        if self.handles.get(msg.from_pid, request.handle).is_none() {
+            return Err(unknown_handle(request.handle));
+        }
+        let slot = *msg
+            .cap_slots
+            .first()
+            .ok_or_else(|| VfsError::InvalidRequest("No Memory capability sent".into()))?;
+        if request.size == 0 || request.size > MAX_SHM_ATTACH_SIZE {
+            return Err(VfsError::InvalidRequest(format!(
+                "Invalid shared memory size {}",
+                request.size
+            )));
+        }
+
+        let region = SharedMemory::map(slot, request.size).map_err(|code| match code {
+            syscall_error::LIMIT_EXCEEDED => VfsError::QuotaExceeded,
+            syscall_error::PERMISSION_DENIED => VfsError::PermissionDenied,
+            _ => VfsError::InvalidRequest(format!("Cannot map shared memory ({})", code)),
+        })?;
+        let len = region.len() as u32;
+        syscall::debug(&format!(
+            "VfsService: attached {} bytes of shared memory to handle {} of PID {}",
+            len, request.handle, msg.from_pid
+        ));
+        self.handles
+            .attach_shm(msg.from_pid, request.handle, region);
+        Ok(len)
+    }
+
+    /// The first `len` bytes of the region attached to a handle of `pid`.
+    fn fetch_shm(&mut self, pid: u32, handle: u32, len: u64) -> Result<Vec<u8>, VfsError> {
+        let region = self.handles.shm_mut(pid, handle).ok_or(no_shm(handle))?;
+        if len > region.len() as u64 {
+            return Err(VfsError::InvalidRequest(format!(
+                "{} bytes exceed the {} byte shared memory region",
+                len,
+                region.len()
+            )));
+        }
+        let len = len as usize;
+        region.fetch(0, len).map_err(|code| {
+            VfsError::StorageError(format!("Shared memory fetch failed ({})", code))
+        })?;
+        Ok(region.as_slice()[..len].to_vec())
+    }
+
+    // =========================================================================
+    // Result handlers
+    // =========================================================================
+
+    /// Handle open inode result: check type and permissions, or create.
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle_open_result(
+        &mut self,
+        client_ctx: &ClientContext,
+        path: &str,
+        perm_ctx: &PermissionContext,
+        writable: bool,
+        create: bool,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let inode = match result_type {
+            ResultKind::ReadOk => match parse_inode(data) {
+                Ok(inode) => inode,
+                Err(e) => {
+                    return self.send_open_error(
+                        client_ctx,
+                        VfsError::StorageError(format!("Failed to parse inode: {}", e)),
+                    );
+                }
+            },
+            ResultKind::NotFound if create => {
+                // Create the file empty; the write state machine checks the
+                // parent and hands out the handle once the inode lands
+                return self.start_storage_read(
+                    &inode_key(&parent_path(path)),
+                    PendingOp::WriteFileOp {
+                        ctx: client_ctx.clone(),
+                        path: path.to_string(),
+                        perm_ctx: perm_ctx.clone(),
+                        stage: WriteFileStage::CheckingParent {
+                            content: Vec::new(),
+                        },
+                        reply: WriteReply::Open { writable },
+                    },
+                );
+            }
+            ResultKind::NotFound => return self.send_open_error(client_ctx, VfsError::NotFound),
+            _ => {
+                return self.send_open_error(
+                    client_ctx,
+                    VfsError::StorageError(format!(
+                        "Inode read failed: {} ({})",
+                        result_type as u8,
+                        result_type.name()
+                    )),
+                );
+            }
+        };
+
+        if !inode.is_file() {
+            return self.send_open_error(client_ctx, VfsError::NotAFile);
+        }
+        if !check_read(&inode, perm_ctx) || (writable && !check_write(&inode, perm_ctx)) {
+            syscall::debug(&format!(
+                "VfsService: Permission denied for open {} (pid={})",
+                path, client_ctx.pid
+            ));
+            return self.send_open_error(client_ctx, VfsError::PermissionDenied);
+        }
+
+        self.open_handle(client_ctx, path, perm_ctx, writable)
+    }
+
+    /// Allocate a handle for a file that passed its checks and reply with it.
+    pub fn open_handle(
+        &mut self,
+        client_ctx: &ClientContext,
+        path: &str,
+        perm_ctx: &PermissionContext,
+        writable: bool,
+    ) -> Result<(), AppError> {
+        let file = OpenFile {
+            path: path.to_string(),
+            writable,
+            perm_ctx: perm_ctx.clone(),
+        };
+        let result = self
+            .handles
+            .open(client_ctx.pid, file)
+            .ok_or(VfsError::QuotaExceeded);
+        let response = OpenResponse { result };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_OPEN_RESPONSE, &response)
+    }
+
+    /// Handle read-at content result (`length` is already capped to what
+    /// the reply can carry)
+    pub fn handle_read_at_result(
+        &mut self,
+        client_ctx: &ClientContext,
+        offset: u64,
+        length: u64,
+        shm: Option<u32>,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        if result_type == ResultKind::ReadOk && is_manifest(data) {
+            let reply = ChunkReadReply::ReadAt {
+                offset,
+                length,
+                shm,
+            };
+            return self.start_chunk_read(client_ctx, data, reply);
+        }
+        let result = match result_type {
+            ResultKind::ReadOk => self
+                .content_keys
+                .open(data)
+                .map(|content| slice_range(&content, offset, length).to_vec()),
+            // The file was removed since it was opened
+            ResultKind::NotFound => Err(VfsError::NotFound),
+            _ => Err(VfsError::StorageError(format!(
+                "Content read failed: {} ({})",
+                result_type as u8,
+                result_type.name()
+            ))),
+        };
+        self.send_read_at_reply(client_ctx, shm, result)
+    }
+
+    /// Answer a read-at with its bytes, placing them in the handle's shared
+    /// memory if the read asked for that.
+    pub fn send_read_at_reply(
+        &mut self,
+        client_ctx: &ClientContext,
+        shm: Option<u32>,
+        result: Result<Vec<u8>, VfsError>,
+    ) -> Result<(), AppError> {
+        let response = match (shm, result) {
+            (Some(handle), Ok(bytes)) => match self.flush_shm(client_ctx.pid, handle, &bytes) {
+                Ok(()) => ReadAtResponse {
+                    result: Ok(Vec::new()),
+                    shm_len: Some(bytes.len() as u64),
+                },
+                Err(error) => ReadAtResponse {
+                    result: Err(error),
+                    shm_len: None,
+                },
+            },
+            (_, result) => ReadAtResponse {
+                result,
+                shm_len: None,
+            },
+        };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_READ_AT_RESPONSE, &response)
+    }
+
+    /// Place `bytes` at the start of the region attached to a handle of
+    /// `pid`. It may have been replaced by a smaller one since the read.
+    fn flush_shm(&mut self, pid: u32, handle: u32, bytes: &[u8]) -> Result<(), VfsError> {
+        let region = match self.handles.shm_mut(pid, handle) {
+            Some(region) if region.len() >= bytes.len() => region,
+            _ => return Err(no_shm(handle)),
+        };
+        region.as_mut_slice()[..bytes.len()].copy_from_slice(bytes);
+        region.flush(0, bytes.len()).map_err(|code| {
+            VfsError::StorageError(format!("Shared memory flush failed ({})", code))
+        })
+    }
+
+    /// Handle write-at content result: splice and commit via the write path.
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle_write_at_result(
+        &mut self,
+        client_ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        offset: u64,
+        write: Vec<u8>,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        if result_type == ResultKind::ReadOk && is_manifest(data) {
+            return self.start_chunk_patch(client_ctx, path, perm_ctx, offset, write, data);
+        }
+        // A blob is rewritten whole, so it can only grow as far as a write
+        // can carry; a chunked file can grow further
+        let end = offset.saturating_add(write.len() as u64);
+        if result_type == ResultKind::ReadOk && end > MAX_CONTENT_SIZE as u64 {
+            return self.send_write_at_error(&client_ctx, VfsError::FileTooLarge);
+        }
+        let content = match result_type {
+            ResultKind::ReadOk => match self.content_keys.open(data) {
+                Ok(content) => splice_at(&content, offset, &write),
+                Err(error) => return self.send_write_at_error(&client_ctx, error),
+            },
+            ResultKind::NotFound => {
+                return self.send_write_at_error(&client_ctx, VfsError::NotFound)
+            }
+            _ => {
+                return self.send_write_at_error(
+                    &client_ctx,
+                    VfsError::StorageError(format!(
+                        "Content read failed: {} ({})",
+                        result_type as u8,
+                        result_type.name()
+                    )),
+                );
+            }
+        };
+
+        let size = content.len() as u64;
+        self.start_storage_read(
+            &inode_key(&parent_path(&path)),
+            PendingOp::WriteFileOp {
+                ctx: client_ctx,
+                path,
+                perm_ctx,
+                stage: WriteFileStage::CheckingParent { content },
+                reply: WriteReply::WriteAt { size },
+            },
+        )
+    }
+
+    // =========================================================================
+    // Response helpers
+    // =========================================================================
+
+    fn send_open_error(&self, client_ctx: &ClientContext, error: VfsError) -> Result<(), AppError> {
+        let response = OpenResponse { result: Err(error) };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_OPEN_RESPONSE, &response)
+    }
+
+    fn send_read_at_error(
+        &self,
+        client_ctx: &ClientContext,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        let response = ReadAtResponse {
+            result: Err(error),
+            shm_len: None,
+        };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_READ_AT_RESPONSE, &response)
+    }
+
+    fn send_write_at_error(
+        &self,
+        client_ctx: &ClientContext,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        let response = WriteAtResponse { result: Err(error) };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_WRITE_AT_RESPONSE, &response)
+    }
+}
+
+fn unknown_handle(handle: u32) -> VfsError {
+    VfsError::InvalidRequest(format!("Unknown handle {}", handle))
+}
+
+fn no_shm(handle: u32) -> VfsError {
+    VfsError::InvalidRequest(format!("No shared memory attached to handle {}", handle))
+}
@@ -0,0 +1,302 @@
+//! Integrity handlers for VFS Service
+//!
+//! Handles: hash, checking reads against content hashes, and loading the
+//! flag that turns those checks on
+//!
+//! Whole-file writes record the SHA-256 of the plaintext in the inode (see
+//! `zos_vfs::storage::integrity`). `MSG_VFS_HASH` reads the inode, then the
+//! content record; a chunked file is fed to the hash one chunk at a time
+//! as a `ChunkReadOp`, so hashing holds one chunk whatever the file's size.
+//! The result is always checked against the recorded hash, if there is
+//! one.
+//!
+//! Whole-file reads are checked only when the `vfs.verify_hashes` flag is
+//! set system-wide. The flag store is read at startup and again whenever it
+//! is rewritten. Reads through handles are never checked: they see part of
+//! a file at a time.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: the hash of exactly the bytes a whole-file read returns
+//! - **Acceptable partial failure**: a hash racing a rewrite of its file can
+//!   fail, as a chunked read would
+//! - **Forbidden**: returning a hash without read permission; returning
+//!   content or a hash that doesn't match the inode's recorded hash
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_flags::{known, FlagStore, FLAGS_PATH};
+use zos_ipc::storage::ResultKind;
+use zos_vfs::ipc::{vfs_msg, HashRequest, HashResponse};
+use zos_vfs::service::{check_read, PermissionContext};
+use zos_vfs::storage::chunking::is_manifest;
+use zos_vfs::storage::integrity::{content_hash, file_hash, verify, ContentHash};
+use zos_vfs::VfsError;
+
+use super::super::{
+    content_key, inode_key, parse_inode, validate_path, ClientContext, PendingOp, VfsService,
+};
+use super::chunks::ChunkReadReply;
+use super::storage_failure;
+
+/// Stages for the hash state machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashStage {
+    /// Reading the inode and checking permissions
+    ReadingInode,
+    /// Reading the content record
+    ReadingContent {
+        /// Hash the inode records, if any
+        expected: Option<ContentHash>,
+    },
+}
+
+/// `content`, if it matches the hash expected of it.
+pub fn checked_content(
+    expected: Option<&ContentHash>,
+    content: Vec<u8>,
+) -> Result<Vec<u8>, VfsError> {
+    if expected.is_some() {
+        verify(expected, &content_hash(&content))?;
+    }
+    Ok(content)
+}
+
+impl VfsService {
+    /// Answer a hash request with an error.
+    pub fn send_hash_error(
+        &self,
+        client_ctx: &ClientContext,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        let response = HashResponse { result: Err(error) };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_HASH_RESPONSE, &response)
+    }
+
+    /// Answer a hash request with `hash`, once checked against `expected`.
+    pub fn send_hash_reply(
+        &self,
+        client_ctx: &ClientContext,
+        expected: Option<ContentHash>,
+        hash: ContentHash,
+        size: u64,
+    ) -> Result<(), AppError> {
+        let response = HashResponse {
+            result: verify(expected.as_ref(), &hash).map(|()| file_hash(&hash, size)),
+        };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_HASH_RESPONSE, &response)
+    }
+
+    /// Handle MSG_VFS_HASH - SHA-256 of a file
+    pub fn handle_hash(&mut self, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let request: HashRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(e) => {
+                return self.send_hash_error(
+                    &client_ctx,
+                    VfsError::InvalidRequest(format!("Failed to parse request: {}", e)),
+                );
+            }
+        };
+        if let Err(reason) = validate_path(&request.path) {
+            return self.send_hash_error(&client_ctx, VfsError::InvalidPath(String::from(reason)));
+        }
+
+        syscall::debug(&format!("VfsService: hash {}", request.path));
+
+        let perm_ctx = self.permission_context(msg.from_pid, &request.path);
+        self.start_storage_read(
+            &inode_key(&request.path),
+            PendingOp::HashOp {
+                ctx: client_ctx,
+                path: request.path,
+                perm_ctx,
+                stage: HashStage::ReadingInode,
+            },
+        )
+    }
+
+    /// Handle hash operation result - dispatches based on stage
+    pub fn handle_hash_op_result(
+        &mut self,
+        client_ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        stage: HashStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        match stage {
+            HashStage::ReadingInode => {
+                let inode = match result_type {
+                    ResultKind::ReadOk => match parse_inode(data) {
+                        Ok(inode) => inode,
+                        Err(e) => {
+                            return self.send_hash_error(
+                                &client_ctx,
+                                VfsError::StorageError(format!("Failed to parse inode: {}", e)),
+                            );
+                        }
+                    },
+                    ResultKind::NotFound => {
+                        return self.send_hash_error(&client_ctx, VfsError::NotFound)
+                    }
+                    _ => {
+                        return self.send_hash_error(
+                            &client_ctx,
+                            storage_failure("Inode read", result_type),
+                        )
+                    }
+                };
+                if !inode.is_file() {
+                    return self.send_hash_error(&client_ctx, VfsError::NotAFile);
+                }
+                if !check_read(&inode, &perm_ctx) {
+                    syscall::debug(&format!(
+                        "VfsService: Permission denied for hash {} (pid={})",
+                        path, client_ctx.pid
+                    ));
+                    return self.send_hash_error(&client_ctx, VfsError::PermissionDenied);
+                }
+
+                let key = content_key(&path);
+                self.start_storage_read(
+                    &key,
+                    PendingOp::HashOp {
+                        ctx: client_ctx,
+                        path,
+                        perm_ctx,
+                        stage: HashStage::ReadingContent {
+                            expected: inode.content_hash,
+                        },
+                    },
+                )
+            }
+            HashStage::ReadingContent { expected } => match result_type {
+                ResultKind::ReadOk if is_manifest(data) => {
+                    self.start_chunk_read(&client_ctx, data, ChunkReadReply::Hash { expected })
+                }
+                ResultKind::ReadOk => match self.content_keys.open(data) {
+                    Ok(content) => {
+                        let size = content.len() as u64;
+                        self.send_hash_reply(&client_ctx, expected, content_hash(&content), size)
+                    }
+                    Err(error) => self.send_hash_error(&client_ctx, error),
+                },
+                ResultKind::NotFound => {
+                    syscall::debug(&format!(
+                        "VfsService: CORRUPTION: Content missing for existing inode {}",
+                        path
+                    ));
+                    self.send_hash_error(
+                        &client_ctx,
+                        VfsError::StorageError("Content missing for existing inode".into()),
+                    )
+                }
+                _ => {
+                    self.send_hash_error(&client_ctx, storage_failure("Content read", result_type))
+                }
+            },
+        }
+    }
+
+    // =========================================================================
+    // Verification flag
+    // =========================================================================
+
+    /// Read the feature flag store, to see whether reads are verified.
+    pub fn start_flags_load(&mut self) {
+        if let Err(e) = self.start_storage_read(&content_key(FLAGS_PATH), PendingOp::LoadFlags) {
+            syscall::debug(&format!(
+                "VfsService: Failed to read flags: {:?}, keeping verify_hashes={}",
+                e, self.verify_hashes
+            ));
+        }
+    }
+
+    /// Flag store read completed.
+    pub fn handle_load_flags_result(
+        &mut self,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let store = match result_type {
+            ResultKind::ReadOk => self
+                .content_keys
+                .open(data)
+                .ok()
+                .and_then(|json| FlagStore::from_json(&json)),
+            ResultKind::NotFound => Some(FlagStore::default()),
+            _ => None,
+        };
+        match store {
+            Some(store) => {
+                self.verify_hashes = store.resolve(None).is_enabled(known::VFS_VERIFY_HASHES);
+                syscall::debug(&format!("VfsService: verify_hashes={}", self.verify_hashes));
+            }
+            None => syscall::debug(&format!(
+                "VfsService: Flag store unreadable ({}), keeping verify_hashes={}",
+                result_type.name(),
+                self.verify_hashes
+            )),
+        }
+        Ok(())
+    }
+
+    /// Hash a whole-file read of `inode_hash`'s file must match, if reads
+    /// are verified.
+    pub fn read_expectation(&self, inode_hash: Option<ContentHash>) -> Option<ContentHash> {
+        inode_hash.filter(|_| self.verify_hashes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_content() {
+        let hash = content_hash(b"hello");
+        assert_eq!(checked_content(None, b"x".to_vec()).unwrap(), b"x");
+        assert_eq!(
+            checked_content(Some(&hash), b"hello".to_vec()).unwrap(),
+            b"hello"
+        );
+        assert!(checked_content(Some(&hash), b"hellO".to_vec())
+            .unwrap_err()
+            .is_corrupt());
+    }
+
+    #[test]
+    fn test_load_flags() {
+        let mut service = VfsService::default();
+        assert!(!service.verify_hashes);
+
+        let mut store = FlagStore::default();
+        store
+            .set_system(known::VFS_VERIFY_HASHES, Some(true))
+            .unwrap();
+        service
+            .handle_load_flags_result(ResultKind::ReadOk, &store.to_json())
+            .unwrap();
+        assert!(service.verify_hashes);
+        assert_eq!(service.read_expectation(Some([7; 32])), Some([7; 32]));
+
+        // Unreadable: keep what we had
+        service
+            .handle_load_flags_result(ResultKind::ReadOk, b"not json")
+            .unwrap();
+        assert!(service.verify_hashes);
+
+        // Missing: built-in default
+        service
+            .handle_load_flags_result(ResultKind::NotFound, &[])
+            .unwrap();
+        assert!(!service.verify_hashes);
+        assert_eq!(service.read_expectation(Some([7; 32])), None);
+    }
+}
@@ -0,0 +1,697 @@
+//! Hard link handlers for VFS Service
+//!
+//! Handles: link, reading through link records, and dropping references to
+//! shared blobs
+//!
+//! Linked files share one blob (see `zos_vfs::storage::blobs`). The first
+//! link of a file moves its content record into a blob and leaves a link
+//! record in its place; later links only add a link record and raise the
+//! blob's count. Everything one link commits goes in a single batch write,
+//! so the count never disagrees with the link records it counts.
+//!
+//! Reads follow a link record to its blob and carry on as if the blob were
+//! the file's own record. Blobs are never rewritten: a write to a linked
+//! file stores a record of its own and drops the file's reference once that
+//! lands, so its other names keep the old content. A write-at into a
+//! chunked blob would have to patch shared chunks, so it is refused.
+//!
+//! Removing or replacing a content record (unlink, rmdir, write) releases
+//! what it held. For a link that lowers the count, and the last reference
+//! deletes the count, the blob, and any chunks the blob names. Changes to
+//! one blob's count run one at a time: a link that finds its blob busy asks
+//! the client to retry, and a release waits its turn in `blob_releases`.
+//!
+//! Every link is charged to its owner like a file of its size, so usage
+//! counts names rather than stored bytes. Only the owner of a file can link
+//! it, so a blob is only ever shared between one user's files.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: every link record names a blob whose count includes it
+//! - **Acceptable partial failure**: a count left too high (a failed
+//!   release, or releases still queued at a restart) orphans its blob; a
+//!   write racing the first link of its file can be undone by it
+//! - **Forbidden**: deleting a blob a link record still names
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, Message};
+use zos_ipc::storage::ResultKind;
+use zos_vfs::ipc::{
+    vfs_msg, LinkRequest, LinkResponse, ReadAtResponse, ReadFileResponse, VfsEventKind,
+    WriteAtResponse,
+};
+use zos_vfs::service::{check_read, check_write, PermissionContext};
+use zos_vfs::storage::blobs::{
+    blob_key, content_address, decode_link, decode_refcount, encode_link, encode_refcount, is_link,
+    refcount_key, to_hex, BlobHash,
+};
+use zos_vfs::storage::chunking::{is_manifest, ChunkManifest};
+use zos_vfs::{parent_path, Inode, VfsError};
+
+use super::super::{
+    content_key, inode_key, parse_inode, validate_path, Charge, ClientContext, LinkStage,
+    PendingOp, ReleaseStage, Reservation, VfsService,
+};
+use super::hash::HashStage;
+use super::storage_failure;
+
+/// What a content record keeps alive besides itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeldContent {
+    /// The chunks a manifest names
+    Chunks(ChunkManifest),
+    /// One reference to a shared blob
+    Blob(BlobHash),
+}
+
+impl HeldContent {
+    /// What `record` holds; `None` for content stored in the record itself.
+    pub fn of(record: &[u8]) -> Result<Option<Self>, VfsError> {
+        if is_manifest(record) {
+            ChunkManifest::decode(record).map(|manifest| Some(Self::Chunks(manifest)))
+        } else if is_link(record) {
+            decode_link(record).map(|hash| Some(Self::Blob(hash)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl PendingOp {
+    /// Blob whose count this operation is reading or changing.
+    pub fn blob(&self) -> Option<&BlobHash> {
+        match self {
+            PendingOp::LinkOp {
+                stage:
+                    LinkStage::ReadingRefcount { hash, .. } | LinkStage::WritingBatch { hash, .. },
+                ..
+            }
+            | PendingOp::ReleaseBlob { hash, .. } => Some(hash),
+            PendingOp::SnapshotOp { stage, .. } => stage.blob(),
+            PendingOp::GcOp { stage } => stage.blob(),
+            _ => None,
+        }
+    }
+
+    /// Whether a link record read by this operation stands for its blob.
+    pub fn follows_links(&self) -> bool {
+        matches!(
+            self,
+            PendingOp::GetContent { .. }
+                | PendingOp::ReadAtOp { .. }
+                | PendingOp::WriteAtOp { .. }
+                | PendingOp::HashOp {
+                    stage: HashStage::ReadingContent { .. },
+                    ..
+                }
+        )
+    }
+}
+
+impl VfsService {
+    /// Send a link error response to the client.
+    fn send_link_error(&self, client_ctx: &ClientContext, error: VfsError) -> Result<(), AppError> {
+        let response = LinkResponse { result: Err(error) };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_LINK_RESPONSE, &response)
+    }
+
+    /// Handle MSG_VFS_LINK - give a file a second name
+    ///
+    /// This starts the link state machine:
+    /// 1. Read source inode, check it's a readable file we own
+    /// 2. Check the link path doesn't exist
+    /// 3. Check its parent is a writable directory, reserve quota
+    /// 4. Read the source content record and the count of its blob
+    /// 5. Write blob, count, link records and inode in one batch
+    pub fn handle_link(&mut self, _ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
+        let request: LinkRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(e) => {
+                let response = LinkResponse {
+                    result: Err(VfsError::InvalidRequest(format!(
+                        "Failed to parse request: {}",
+                        e
+                    ))),
+                };
+                return self.send_response_via_debug(
+                    msg.from_pid,
+                    vfs_msg::MSG_VFS_LINK_RESPONSE,
+                    &response,
+                );
+            }
+        };
+
+        let invalid = if let Err(reason) = validate_path(&request.path) {
+            Some(VfsError::InvalidPath(String::from(reason)))
+        } else if let Err(reason) = validate_path(&request.link_path) {
+            Some(VfsError::InvalidPath(String::from(reason)))
+        } else if request.link_path == "/" {
+            Some(VfsError::AlreadyExists)
+        } else {
+            None
+        };
+        if let Some(error) = invalid {
+            let response = LinkResponse { result: Err(error) };
+            return self.send_response_via_debug(
+                msg.from_pid,
+                vfs_msg::MSG_VFS_LINK_RESPONSE,
+                &response,
+            );
+        }
+
+        syscall::debug(&format!(
+            "VfsService: link {} -> {}",
+            request.link_path, request.path
+        ));
+
+        // The new inode belongs to the user the link path does
+        let perm_ctx = self.permission_context(msg.from_pid, &request.link_path);
+        let client_ctx = self.client_context(msg);
+
+        self.start_storage_read(
+            &inode_key(&request.path),
+            PendingOp::LinkOp {
+                ctx: client_ctx,
+                path: request.path,
+                link_path: request.link_path,
+                perm_ctx,
+                stage: LinkStage::ReadingSource,
+            },
+        )
+    }
+
+    /// Handle link operation result - dispatches based on stage
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle_link_op_result(
+        &mut self,
+        client_ctx: ClientContext,
+        path: String,
+        link_path: String,
+        perm_ctx: PermissionContext,
+        stage: LinkStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let next = match stage {
+            LinkStage::ReadingSource => self.link_reading_source(
+                &client_ctx,
+                &path,
+                &link_path,
+                &perm_ctx,
+                result_type,
+                data,
+            ),
+            LinkStage::CheckingTarget { inode } => match result_type {
+                ResultKind::NotFound => Ok((
+                    inode_key(&parent_path(&link_path)),
+                    LinkStage::CheckingParent { inode },
+                )),
+                ResultKind::ReadOk => Err(VfsError::AlreadyExists),
+                _ => Err(storage_failure("Target check", result_type)),
+            },
+            LinkStage::CheckingParent { inode } => self
+                .link_checking_parent(&client_ctx, &link_path, &perm_ctx, result_type, data)
+                .and_then(|()| {
+                    // A link counts against its owner like any file its size
+                    let reservation = self.quotas.reserve(Charge::of(&inode), None)?;
+                    Ok((
+                        content_key(&path),
+                        LinkStage::ReadingContent { inode, reservation },
+                    ))
+                }),
+            LinkStage::ReadingContent { inode, reservation } => {
+                let next = self.link_reading_content(inode, reservation, result_type, data);
+                if next.is_err() {
+                    self.quotas.undo(&reservation);
+                }
+                next
+            }
+            LinkStage::ReadingRefcount {
+                inode,
+                reservation,
+                hash,
+                record,
+            } => {
+                let count = match result_type {
+                    ResultKind::ReadOk => decode_refcount(data),
+                    // No blob yet: the source is about to become it
+                    ResultKind::NotFound if record.is_some() => Ok(0),
+                    ResultKind::NotFound => Err(VfsError::StorageError(format!(
+                        "Blob {} has no refcount",
+                        to_hex(&hash)
+                    ))),
+                    _ => Err(storage_failure("Refcount read", result_type)),
+                };
+                return match count {
+                    Ok(count) => self.link_write_batch(
+                        client_ctx,
+                        path,
+                        link_path,
+                        perm_ctx,
+                        *inode,
+                        reservation,
+                        hash,
+                        record,
+                        count,
+                    ),
+                    Err(error) => {
+                        self.quotas.undo(&reservation);
+                        self.fail_link(&client_ctx, &path, &link_path, error)
+                    }
+                };
+            }
+            LinkStage::WritingBatch { reservation, .. } => {
+                if result_type != ResultKind::WriteOk {
+                    self.quotas.undo(&reservation);
+                    return self.fail_link(
+                        &client_ctx,
+                        &path,
+                        &link_path,
+                        storage_failure("Link write", result_type),
+                    );
+                }
+                syscall::debug(&format!(
+                    "VfsService: link {} -> {} completed successfully",
+                    link_path, path
+                ));
+                self.notify_watchers(VfsEventKind::Create, &link_path, None);
+                let response = LinkResponse { result: Ok(()) };
+                return self.send_response(&client_ctx, vfs_msg::MSG_VFS_LINK_RESPONSE, &response);
+            }
+        };
+
+        match next {
+            Ok((key, stage)) => self.start_storage_read(
+                &key,
+                PendingOp::LinkOp {
+                    ctx: client_ctx,
+                    path,
+                    link_path,
+                    perm_ctx,
+                    stage,
+                },
+            ),
+            Err(error) => self.fail_link(&client_ctx, &path, &link_path, error),
+        }
+    }
+
+    fn fail_link(
+        &self,
+        client_ctx: &ClientContext,
+        path: &str,
+        link_path: &str,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        syscall::debug(&format!(
+            "VfsService: link {} -> {} failed: {:?}",
+            link_path, path, error
+        ));
+        self.send_link_error(client_ctx, error)
+    }
+
+    /// Stage 1: Source inode read - must be a readable file we own
+    fn link_reading_source(
+        &self,
+        client_ctx: &ClientContext,
+        path: &str,
+        link_path: &str,
+        perm_ctx: &PermissionContext,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(String, LinkStage), VfsError> {
+        match result_type {
+            ResultKind::ReadOk => {}
+            ResultKind::NotFound => return Err(VfsError::NotFound),
+            _ => return Err(storage_failure("Inode read", result_type)),
+        }
+
+        // FAIL CLOSED on parse error
+        let inode = parse_inode(data)
+            .map(Box::new)
+            .map_err(|e| VfsError::StorageError(format!("Failed to parse inode: {}", e)))?;
+        if !inode.is_file() {
+            return Err(VfsError::NotAFile);
+        }
+        // Both names share one sealed blob and one owner's quota
+        if !check_read(&inode, perm_ctx) || inode.owner_id != perm_ctx.user_id {
+            syscall::debug(&format!(
+                "VfsService: Permission denied for link {} -> {} (pid={})",
+                link_path, path, client_ctx.pid
+            ));
+            return Err(VfsError::PermissionDenied);
+        }
+
+        Ok((inode_key(link_path), LinkStage::CheckingTarget { inode }))
+    }
+
+    /// Stage 3: Link parent read - must be a directory we can write to
+    fn link_checking_parent(
+        &self,
+        client_ctx: &ClientContext,
+        link_path: &str,
+        perm_ctx: &PermissionContext,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), VfsError> {
+        match result_type {
+            ResultKind::ReadOk => {}
+            ResultKind::NotFound => return Err(VfsError::NotFound),
+            _ => return Err(storage_failure("Parent read", result_type)),
+        }
+
+        // SECURITY: Fail closed - a corrupt parent must not bypass the check
+        let parent = parse_inode(data).map_err(|e| {
+            VfsError::StorageError(format!("Parent inode corrupt or invalid: {}", e))
+        })?;
+        if !parent.is_directory() {
+            return Err(VfsError::NotADirectory);
+        }
+        if !check_write(&parent, perm_ctx) {
+            syscall::debug(&format!(
+                "VfsService: Permission denied for link {} in {} (pid={})",
+                link_path, parent.path, client_ctx.pid
+            ));
+            return Err(VfsError::PermissionDenied);
+        }
+
+        Ok(())
+    }
+
+    /// Stage 4: Source content read - find the blob it is or names
+    fn link_reading_content(
+        &self,
+        inode: Box<Inode>,
+        reservation: Reservation,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(String, LinkStage), VfsError> {
+        let (hash, record) = match result_type {
+            ResultKind::ReadOk if is_link(data) => (decode_link(data)?, None),
+            ResultKind::ReadOk => (content_address(data), Some(data.to_vec())),
+            ResultKind::NotFound => {
+                return Err(VfsError::StorageError(
+                    "Content missing for existing inode".into(),
+                ))
+            }
+            _ => return Err(storage_failure("Content read", result_type)),
+        };
+        if self.blob_in_use(&hash) {
+            return Err(VfsError::retry("Linked content is being updated"));
+        }
+        Ok((
+            refcount_key(&hash),
+            LinkStage::ReadingRefcount {
+                inode,
+                reservation,
+                hash,
+                record,
+            },
+        ))
+    }
+
+    /// Stage 5: Commit the link in one batch.
+    ///
+    /// On the first link of a file the source's own record becomes the
+    /// blob (unless an identical one is already stored) and is replaced by
+    /// a link record, so the count goes up by two.
+    #[allow(clippy::too_many_arguments)]
+    fn link_write_batch(
+        &mut self,
+        client_ctx: ClientContext,
+        path: String,
+        link_path: String,
+        perm_ctx: PermissionContext,
+        mut inode: Inode,
+        reservation: Reservation,
+        hash: BlobHash,
+        record: Option<Vec<u8>>,
+        count: u64,
+    ) -> Result<(), AppError> {
+        inode.path = link_path.clone();
+        inode.parent_path = parent_path(&link_path);
+        inode.name = link_path
+            .rsplit('/')
+            .next()
+            .unwrap_or(&link_path)
+            .to_string();
+        inode.created_at = syscall::get_wallclock();
+        let inode_json = match serde_json::to_vec(&inode) {
+            Ok(j) => j,
+            Err(e) => {
+                self.quotas.undo(&reservation);
+                return self.fail_link(
+                    &client_ctx,
+                    &path,
+                    &link_path,
+                    VfsError::StorageError(format!("Failed to serialize inode: {}", e)),
+                );
+            }
+        };
+
+        let link = encode_link(&hash);
+        let added = if record.is_some() { 2 } else { 1 };
+        let keys = (
+            blob_key(&hash),
+            refcount_key(&hash),
+            content_key(&path),
+            content_key(&link_path),
+            inode_key(&link_path),
+        );
+        let refcount = encode_refcount(count + added);
+        let mut items: Vec<(&str, &[u8])> = Vec::with_capacity(5);
+        if let Some(record) = &record {
+            if count == 0 {
+                items.push((&keys.0, record));
+            }
+        }
+        items.push((&keys.1, &refcount));
+        if record.is_some() {
+            items.push((&keys.2, &link));
+        }
+        items.push((&keys.3, &link));
+        items.push((&keys.4, &inode_json));
+
+        let started = self.start_storage_batch_write(
+            &items,
+            PendingOp::LinkOp {
+                ctx: client_ctx,
+                path,
+                link_path,
+                perm_ctx,
+                stage: LinkStage::WritingBatch { reservation, hash },
+            },
+        );
+        if started.is_err() {
+            self.quotas.undo(&reservation);
+        }
+        started
+    }
+
+    // =========================================================================
+    // Following links
+    // =========================================================================
+
+    /// Continue `then` with the blob a link record names instead of the
+    /// record.
+    pub fn follow_link(&mut self, then: PendingOp, record: &[u8]) -> Result<(), AppError> {
+        match decode_link(record) {
+            Ok(hash) => self.start_storage_read(
+                &blob_key(&hash),
+                PendingOp::FollowLink {
+                    then: Box::new(then),
+                },
+            ),
+            Err(error) => self.fail_followed(then, error),
+        }
+    }
+
+    /// Handle the blob read for a followed link.
+    pub fn handle_follow_link_result(
+        &mut self,
+        ctx: &AppContext,
+        then: PendingOp,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let error = match result_type {
+            ResultKind::ReadOk if is_link(data) => {
+                Some(VfsError::StorageError("Blob is itself a link".into()))
+            }
+            ResultKind::ReadOk
+                if is_manifest(data) && matches!(then, PendingOp::WriteAtOp { .. }) =>
+            {
+                Some(VfsError::NotSupported(
+                    "Write-at into a linked file larger than one chunk".into(),
+                ))
+            }
+            ResultKind::NotFound => Some(VfsError::StorageError("Linked content missing".into())),
+            _ => None,
+        };
+        match error {
+            Some(error) => self.fail_followed(then, error),
+            None => self.dispatch_storage_result(ctx, then, result_type, data),
+        }
+    }
+
+    /// Answer the client of a followed link with an error.
+    fn fail_followed(&self, then: PendingOp, error: VfsError) -> Result<(), AppError> {
+        syscall::debug(&format!("VfsService: following link failed: {:?}", error));
+        match then {
+            PendingOp::GetContent { ctx, .. } => {
+                let response = ReadFileResponse { result: Err(error) };
+                self.send_response(&ctx, vfs_msg::MSG_VFS_READ_RESPONSE, &response)
+            }
+            PendingOp::ReadAtOp { ctx, .. } => {
+                let response = ReadAtResponse {
+                    result: Err(error),
+                    shm_len: None,
+                };
+                self.send_response(&ctx, vfs_msg::MSG_VFS_READ_AT_RESPONSE, &response)
+            }
+            PendingOp::WriteAtOp { ctx, .. } => {
+                let response = WriteAtResponse { result: Err(error) };
+                self.send_response(&ctx, vfs_msg::MSG_VFS_WRITE_AT_RESPONSE, &response)
+            }
+            PendingOp::HashOp { ctx, .. } => self.send_hash_error(&ctx, error),
+            _ => Ok(()),
+        }
+    }
+
+    // =========================================================================
+    // Releasing
+    // =========================================================================
+
+    /// Whether a link or release is reading or changing the count of the
+    /// blob at `hash`.
+    pub fn blob_in_use(&self, hash: &BlobHash) -> bool {
+        self.pending_ops.values().any(|op| op.blob() == Some(hash))
+    }
+
+    /// Let go of what a content record held, once the record is gone.
+    pub fn release_held(&mut self, held: HeldContent) {
+        match held {
+            HeldContent::Chunks(manifest) => self.start_chunk_release(manifest),
+            HeldContent::Blob(hash) => {
+                self.blob_releases.push(hash);
+                self.pump_blob_releases();
+            }
+        }
+    }
+
+    /// Start the queued releases whose blobs nothing else is changing.
+    pub fn pump_blob_releases(&mut self) {
+        for hash in core::mem::take(&mut self.blob_releases) {
+            if self.blob_in_use(&hash) || !self.start_blob_release(hash) {
+                self.blob_releases.push(hash);
+            }
+        }
+    }
+
+    fn start_blob_release(&mut self, hash: BlobHash) -> bool {
+        // Releasing works for no client, so no request deadline applies
+        let deadline = self.request_deadline.take();
+        let started = self.start_storage_read(
+            &refcount_key(&hash),
+            PendingOp::ReleaseBlob {
+                hash,
+                stage: ReleaseStage::ReadingCount,
+            },
+        );
+        self.request_deadline = deadline;
+        started.is_ok()
+    }
+
+    /// Handle a step of dropping a blob reference.
+    pub fn handle_release_blob_result(
+        &mut self,
+        hash: BlobHash,
+        stage: ReleaseStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let next = match (stage, result_type) {
+            (ReleaseStage::ReadingCount, ResultKind::ReadOk) => match decode_refcount(data) {
+                Ok(count) if count > 1 => Some((
+                    refcount_key(&hash),
+                    Some(encode_refcount(count - 1)),
+                    ReleaseStage::WritingCount,
+                )),
+                Ok(_) => Some((blob_key(&hash), None, ReleaseStage::ReadingBlob)),
+                Err(e) => {
+                    self.orphan_blob(&hash, &format!("{:?}", e));
+                    None
+                }
+            },
+            (ReleaseStage::WritingCount, ResultKind::WriteOk) => None,
+            (ReleaseStage::ReadingBlob, ResultKind::ReadOk | ResultKind::NotFound) => {
+                // Chunks a damaged manifest names can't be found; the blob
+                // still goes
+                let manifest = (result_type == ResultKind::ReadOk && is_manifest(data))
+                    .then(|| ChunkManifest::decode(data).ok())
+                    .flatten();
+                // The count goes first: a blob without one is only orphaned,
+                // never mistaken for a live one
+                Some((
+                    refcount_key(&hash),
+                    None,
+                    ReleaseStage::DeletingCount { manifest },
+                ))
+            }
+            (
+                ReleaseStage::DeletingCount { manifest },
+                ResultKind::WriteOk | ResultKind::NotFound,
+            ) => Some((
+                blob_key(&hash),
+                None,
+                ReleaseStage::DeletingBlob { manifest },
+            )),
+            (
+                ReleaseStage::DeletingBlob { manifest },
+                ResultKind::WriteOk | ResultKind::NotFound,
+            ) => {
+                if let Some(manifest) = manifest {
+                    self.start_chunk_release(manifest);
+                }
+                None
+            }
+            (stage, result_type) => {
+                self.orphan_blob(
+                    &hash,
+                    &format!(
+                        "{:?}: {} ({})",
+                        stage,
+                        result_type as u8,
+                        result_type.name()
+                    ),
+                );
+                None
+            }
+        };
+
+        let Some((key, value, stage)) = next else {
+            return Ok(());
+        };
+        let op = PendingOp::ReleaseBlob { hash, stage };
+        let started = match (stage, value) {
+            (ReleaseStage::ReadingBlob, _) => self.start_storage_read(&key, op),
+            (_, Some(value)) => self.start_storage_write(&key, &value, op),
+            (_, None) => self.start_storage_delete(&key, op),
+        };
+        if started.is_err() {
+            self.orphan_blob(&hash, "cannot start storage operation");
+        }
+        Ok(())
+    }
+
+    fn orphan_blob(&self, hash: &BlobHash, reason: &str) {
+        syscall::debug(&format!(
+            "VfsService: Blob {} not released ({}) - orphaned",
+            to_hex(hash),
+            reason
+        ));
+    }
+}
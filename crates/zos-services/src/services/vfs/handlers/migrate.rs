@@ -0,0 +1,484 @@
+//! Background schema migration for stored inodes
+//!
+//! Reads already upgrade old inodes in memory (see `parse_inode`). This
+//! sweep walks the tree from `/` after startup and writes upgraded inodes
+//! back, so old layouts do not linger in storage indefinitely.
+//!
+//! The same walk seals owned files whose content predates encryption (see
+//! `encryption`): the content is rewritten sealed, then the inode is marked
+//! encrypted. A sweep finished by a build with an older content format runs
+//! again for this.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: every reachable inode is stored at the current schema version
+//! - **Acceptable partial failure**: an inode is left at its old version (it
+//!   is still upgraded lazily on read and retried on the next sweep)
+//! - **Forbidden**: overwriting a client's newer write with a migrated copy
+//!
+//! Storage results are delivered in request order, so a client write issued
+//! before the sweep's read is visible to that read, and one issued after the
+//! write-back lands after it. The only window is a client mutation still in
+//! flight when the sweep's read completes; the write-back is skipped then.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zos_apps::syscall;
+use zos_apps::AppError;
+use zos_ipc::storage::ResultKind;
+use zos_vfs::schema::{decode_inode, INODE_SCHEMA_VERSION};
+use zos_vfs::storage::blobs::is_link;
+use zos_vfs::storage::chunking::is_manifest;
+use zos_vfs::storage::encryption::{self, CONTENT_FORMAT_VERSION};
+use zos_vfs::Inode;
+
+use super::super::{content_key, inode_key, InodeOpType, PendingOp, SealStage, VfsService};
+
+/// Maximum sweep storage operations in flight at once.
+///
+/// Kept small so client requests are never starved of `MAX_PENDING_OPS`.
+pub const MIGRATION_CONCURRENCY: usize = 4;
+
+/// State of the background migration sweep.
+#[derive(Default)]
+pub struct MigrationSweep {
+    /// Whether the sweep has been started
+    started: bool,
+    /// Whether the completion summary has been logged
+    finished: bool,
+    /// Paths whose inode is yet to be read
+    queue: Vec<String>,
+    /// Sweep storage operations awaiting results
+    in_flight: usize,
+    /// Inodes read
+    scanned: u32,
+    /// Inodes written back at the current version
+    upgraded: u32,
+    /// Plaintext files sealed
+    sealed: u32,
+    /// Old inodes left as-is (busy, or write-back failed)
+    deferred: u32,
+    /// Inodes that could not be decoded or listed
+    failed: u32,
+}
+
+impl MigrationSweep {
+    /// Whether the sweep has started and has no more work.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Sweep progress as saved in the service checkpoint.
+///
+/// Paths with a sweep operation in flight are saved back in the queue; after
+/// a restart they are simply read again.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SweepProgress {
+    /// Inode schema version the sweep was upgrading to
+    pub target_version: u32,
+    /// Content format version the sweep was sealing to
+    #[serde(default)]
+    pub content_format: u8,
+    pub finished: bool,
+    pub queue: Vec<String>,
+    pub scanned: u32,
+    pub upgraded: u32,
+    #[serde(default)]
+    pub sealed: u32,
+    pub deferred: u32,
+    pub failed: u32,
+}
+
+impl VfsService {
+    /// Start the migration sweep from the root directory.
+    pub fn start_migration_sweep(&mut self) {
+        if self.migration.started {
+            return;
+        }
+        self.migration.started = true;
+        self.migration.queue.push(String::from("/"));
+        self.pump_migration_sweep();
+    }
+
+    /// Continue a sweep saved before a restart.
+    ///
+    /// A sweep saved by a build with a different inode schema or content
+    /// format starts over.
+    pub fn resume_migration_sweep(&mut self, progress: SweepProgress) {
+        if self.migration.started
+            || progress.target_version != INODE_SCHEMA_VERSION
+            || progress.content_format != CONTENT_FORMAT_VERSION
+        {
+            self.start_migration_sweep();
+            return;
+        }
+        syscall::debug(&format!(
+            "VfsService: Resuming schema migration sweep ({} paths queued)",
+            progress.queue.len()
+        ));
+        self.migration = MigrationSweep {
+            started: true,
+            finished: progress.finished,
+            queue: progress.queue,
+            in_flight: 0,
+            scanned: progress.scanned,
+            upgraded: progress.upgraded,
+            sealed: progress.sealed,
+            deferred: progress.deferred,
+            failed: progress.failed,
+        };
+        self.pump_migration_sweep();
+    }
+
+    /// Snapshot of the sweep for checkpointing (`None` before it starts).
+    pub fn migration_progress(&self) -> Option<SweepProgress> {
+        let sweep = &self.migration;
+        if !sweep.started {
+            return None;
+        }
+        let mut queue = sweep.queue.clone();
+        queue.extend(self.pending_ops.values().filter_map(|op| match op {
+            PendingOp::MigrateInode { path }
+            | PendingOp::MigrateList { path }
+            | PendingOp::MigrateWrite { path }
+            | PendingOp::MigrateSeal { path, .. } => Some(path.clone()),
+            _ => None,
+        }));
+        Some(SweepProgress {
+            target_version: INODE_SCHEMA_VERSION,
+            content_format: CONTENT_FORMAT_VERSION,
+            finished: sweep.finished,
+            queue,
+            scanned: sweep.scanned,
+            upgraded: sweep.upgraded,
+            sealed: sweep.sealed,
+            deferred: sweep.deferred,
+            failed: sweep.failed,
+        })
+    }
+
+    /// Issue queued sweep reads up to the concurrency limit, and log a
+    /// summary once the sweep drains.
+    pub fn pump_migration_sweep(&mut self) {
+        // A draining service only finishes what it already issued; the
+        // rest of the queue is checkpointed and resumed after the restart
+        if !self.migration.started || self.migration.finished || self.drain.is_draining() {
+            return;
+        }
+
+        while self.migration.in_flight < MIGRATION_CONCURRENCY {
+            let Some(path) = self.migration.queue.pop() else {
+                break;
+            };
+            let op = PendingOp::MigrateInode { path: path.clone() };
+            match self.start_storage_read(&inode_key(&path), op) {
+                Ok(()) => self.migration.in_flight += 1,
+                Err(_) => {
+                    // Too busy - retry on the next update
+                    self.migration.queue.push(path);
+                    return;
+                }
+            }
+        }
+
+        let sweep = &mut self.migration;
+        if sweep.queue.is_empty() && sweep.in_flight == 0 {
+            sweep.finished = true;
+            syscall::debug(&format!(
+                "VfsService: Schema migration sweep done (scanned={}, upgraded={}, sealed={}, deferred={}, failed={})",
+                sweep.scanned, sweep.upgraded, sweep.sealed, sweep.deferred, sweep.failed
+            ));
+        }
+    }
+
+    /// Whether any client operation that may write or delete an inode is in
+    /// flight, including one parked on a content key.
+    pub(crate) fn has_inode_mutation_in_flight(&self) -> bool {
+        let mut ops = self.pending_ops.values().chain(self.content_keys.waiting_ops());
+        ops.any(|op| {
+            let op = match op {
+                PendingOp::ContentDeleteOp { then, .. } | PendingOp::FollowLink { then } => then,
+                op => op,
+            };
+            matches!(
+                op,
+                PendingOp::PutInode { .. }
+                    | PendingOp::DeleteInode { .. }
+                    | PendingOp::WriteFileOp { .. }
+                    | PendingOp::OpenFileOp { create: true, .. }
+                    | PendingOp::WriteAtOp { .. }
+                    | PendingOp::ChunkPatchOp { .. }
+                    | PendingOp::MkdirOp { .. }
+                    | PendingOp::CheckExistsForMkdir { .. }
+                    | PendingOp::UnlinkOp { .. }
+                    | PendingOp::RenameOp { .. }
+                    | PendingOp::LinkOp { .. }
+                    | PendingOp::RmdirOp { .. }
+                    | PendingOp::GetInode {
+                        op_type: InodeOpType::MkdirCheckParent { .. }
+                            | InodeOpType::WriteFileCheckParent { .. }
+                            | InodeOpType::Rmdir { .. }
+                            | InodeOpType::Unlink,
+                        ..
+                    }
+            )
+        })
+    }
+
+    /// Sweep inode read completed - write back if old, descend if directory.
+    pub fn handle_migrate_inode_result(
+        &mut self,
+        path: &str,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        self.migration.in_flight = self.migration.in_flight.saturating_sub(1);
+
+        if result_type == ResultKind::ReadOk {
+            self.migration.scanned += 1;
+            match decode_inode(data) {
+                Ok(upgraded) => {
+                    if let Some(from) = upgraded.migrated_from {
+                        self.write_back_migrated_inode(path, from, &upgraded.record);
+                    }
+                    if upgraded.record.is_directory() {
+                        self.start_migration_list(path);
+                    } else if needs_sealing(&upgraded.record) {
+                        self.start_migration_seal(path, upgraded.record);
+                    }
+                }
+                Err(e) => {
+                    self.migration.failed += 1;
+                    syscall::debug(&format!(
+                        "VfsService: Cannot migrate inode {}: {}",
+                        path,
+                        e.message()
+                    ));
+                }
+            }
+        } else if result_type != ResultKind::NotFound {
+            self.migration.failed += 1;
+            syscall::debug(&format!(
+                "VfsService: Migration read of {} failed: {} ({})",
+                path,
+                result_type as u8,
+                result_type.name()
+            ));
+        }
+
+        self.pump_migration_sweep();
+        Ok(())
+    }
+
+    /// Sweep directory listing completed - queue the children.
+    pub fn handle_migrate_list_result(
+        &mut self,
+        path: &str,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        self.migration.in_flight = self.migration.in_flight.saturating_sub(1);
+
+        match result_type {
+            ResultKind::ListOk => match serde_json::from_slice::<Vec<String>>(data) {
+                Ok(children) => self
+                    .migration
+                    .queue
+                    .extend(children.into_iter().filter(|child| child != path)),
+                Err(e) => {
+                    self.migration.failed += 1;
+                    syscall::debug(&format!(
+                        "VfsService: Migration listing of {} unreadable: {}",
+                        path, e
+                    ));
+                }
+            },
+            ResultKind::NotFound => {}
+            _ => self.migration.failed += 1,
+        }
+
+        self.pump_migration_sweep();
+        Ok(())
+    }
+
+    /// Sweep inode write-back completed.
+    pub fn handle_migrate_write_result(
+        &mut self,
+        path: &str,
+        result_type: ResultKind,
+    ) -> Result<(), AppError> {
+        self.migration.in_flight = self.migration.in_flight.saturating_sub(1);
+
+        if result_type == ResultKind::WriteOk {
+            self.migration.upgraded += 1;
+        } else {
+            self.migration.deferred += 1;
+            syscall::debug(&format!(
+                "VfsService: Migration write-back of {} failed: {} ({})",
+                path,
+                result_type as u8,
+                result_type.name()
+            ));
+        }
+
+        self.pump_migration_sweep();
+        Ok(())
+    }
+
+    fn write_back_migrated_inode(&mut self, path: &str, from: u32, inode: &zos_vfs::Inode) {
+        if self.has_inode_mutation_in_flight() {
+            self.migration.deferred += 1;
+            return;
+        }
+
+        let Ok(data) = serde_json::to_vec(inode) else {
+            self.migration.failed += 1;
+            return;
+        };
+        syscall::debug(&format!(
+            "VfsService: Migrating inode {} from schema v{}",
+            path, from
+        ));
+        let op = PendingOp::MigrateWrite {
+            path: path.to_string(),
+        };
+        match self.start_storage_write(&inode_key(path), &data, op) {
+            Ok(()) => self.migration.in_flight += 1,
+            Err(_) => self.migration.deferred += 1,
+        }
+    }
+
+    fn start_migration_list(&mut self, path: &str) {
+        let op = PendingOp::MigrateList {
+            path: path.to_string(),
+        };
+        match self.start_storage_list(&inode_key(path), op) {
+            Ok(()) => self.migration.in_flight += 1,
+            Err(_) => self.migration.failed += 1,
+        }
+    }
+
+    /// Sweep step of sealing a plaintext file completed.
+    pub fn handle_migrate_seal_result(
+        &mut self,
+        path: &str,
+        inode: Inode,
+        stage: SealStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        self.migration.in_flight = self.migration.in_flight.saturating_sub(1);
+
+        match (stage, result_type) {
+            (SealStage::ReadingContent, ResultKind::ReadOk) if encryption::is_sealed(data) => {
+                // Sealed by an earlier sweep that never got to the inode
+                self.write_back_sealed_inode(path, inode);
+            }
+            // Chunks are sealed as they are written, so a chunked file only
+            // needs its inode marked
+            (SealStage::ReadingContent, ResultKind::ReadOk) if is_manifest(data) => {
+                self.write_back_sealed_inode(path, inode);
+            }
+            // A blob is shared by every link to it and never rewritten in
+            // place; the file is sealed whenever it is next written
+            (SealStage::ReadingContent, ResultKind::ReadOk) if is_link(data) => {}
+            (SealStage::ReadingContent, ResultKind::ReadOk) => {
+                self.write_back_sealed_content(path, inode, data);
+            }
+            // An empty file may have no content blob at all
+            (SealStage::ReadingContent, ResultKind::NotFound) => {}
+            (SealStage::WritingContent, ResultKind::WriteOk) => {
+                self.write_back_sealed_inode(path, inode);
+            }
+            (SealStage::WritingInode, ResultKind::WriteOk) => self.migration.sealed += 1,
+            (_, result_type) => {
+                self.migration.deferred += 1;
+                syscall::debug(&format!(
+                    "VfsService: Sealing {} failed: {} ({})",
+                    path,
+                    result_type as u8,
+                    result_type.name()
+                ));
+            }
+        }
+
+        self.pump_migration_sweep();
+        Ok(())
+    }
+
+    fn start_migration_seal(&mut self, path: &str, inode: Inode) {
+        if self.has_inode_mutation_in_flight() {
+            self.migration.deferred += 1;
+            return;
+        }
+        let op = PendingOp::MigrateSeal {
+            path: path.to_string(),
+            inode,
+            stage: SealStage::ReadingContent,
+        };
+        match self.start_storage_read(&content_key(path), op) {
+            Ok(()) => self.migration.in_flight += 1,
+            Err(_) => self.migration.deferred += 1,
+        }
+    }
+
+    fn write_back_sealed_content(&mut self, path: &str, inode: Inode, plaintext: &[u8]) {
+        // A client write in flight would be overwritten with the old content
+        if self.has_inode_mutation_in_flight() {
+            self.migration.deferred += 1;
+            return;
+        }
+        let Some(owner) = inode.owner_id else {
+            return;
+        };
+        let blob = match self.content_keys.seal(owner, plaintext) {
+            Ok(blob) => blob,
+            Err(e) => {
+                self.migration.failed += 1;
+                syscall::debug(&format!("VfsService: Cannot seal {}: {:?}", path, e));
+                return;
+            }
+        };
+        syscall::debug(&format!("VfsService: Sealing plaintext file {}", path));
+        let op = PendingOp::MigrateSeal {
+            path: path.to_string(),
+            inode,
+            stage: SealStage::WritingContent,
+        };
+        match self.start_storage_write(&content_key(path), &blob, op) {
+            Ok(()) => self.migration.in_flight += 1,
+            Err(_) => self.migration.deferred += 1,
+        }
+    }
+
+    fn write_back_sealed_inode(&mut self, path: &str, mut inode: Inode) {
+        // Readers go by the blob itself, so an inode left unmarked here
+        // only misreports the file as plaintext
+        if self.has_inode_mutation_in_flight() {
+            self.migration.deferred += 1;
+            return;
+        }
+        inode.encrypted = true;
+        let Ok(data) = serde_json::to_vec(&inode) else {
+            self.migration.failed += 1;
+            return;
+        };
+        let op = PendingOp::MigrateSeal {
+            path: path.to_string(),
+            inode,
+            stage: SealStage::WritingInode,
+        };
+        match self.start_storage_write(&inode_key(path), &data, op) {
+            Ok(()) => self.migration.in_flight += 1,
+            Err(_) => self.migration.deferred += 1,
+        }
+    }
+}
+
+/// Whether `inode` is an owned file whose content may still be plaintext.
+pub fn needs_sealing(inode: &Inode) -> bool {
+    inode.is_file() && inode.owner_id.is_some() && !inode.encrypted
+}
@@ -1,5 +1,40 @@
 //! VFS Service handlers module
 
+pub mod batch;
+pub mod callers;
+pub mod cancel;
+pub mod checkpoint;
+pub mod chunks;
+pub mod deadline;
 pub mod delete;
+pub mod drain;
+pub mod encryption;
+pub mod gc;
+pub mod handles;
+pub mod hash;
+pub mod link;
+pub mod migrate;
+pub mod mount;
+pub mod quota;
 pub mod read;
+pub mod rename;
+pub mod signed;
+pub mod snapshot;
+pub mod trash;
+pub mod watch;
 pub mod write;
+pub mod xattr;
+
+use alloc::format;
+use zos_ipc::storage::ResultKind;
+use zos_vfs::VfsError;
+
+/// Error for a storage step that returned an unexpected result.
+fn storage_failure(step: &str, result_type: ResultKind) -> VfsError {
+    VfsError::StorageError(format!(
+        "{} failed: {} ({})",
+        step,
+        result_type as u8,
+        result_type.name()
+    ))
+}
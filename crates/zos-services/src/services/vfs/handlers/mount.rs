@@ -0,0 +1,493 @@
+//! Mount handlers for VFS Service
+//!
+//! Handles: mount, umount, mount listing, and routing requests to the mount
+//! that serves their path
+//!
+//! `/` is always mounted on storage. Memory and asset mounts are served
+//! in-process by a `MountedFs`, so requests under them complete in
+//! `on_message` without touching storage; everything else continues down
+//! the storage state machines. A read-only storage mount only changes what
+//! those state machines may do: requests that would modify it are refused
+//! before they start.
+//!
+//! At startup the service mounts `/tmp` on memory with a size limit of
+//! `DEFAULT_TMP_SIZE_LIMIT`, so scratch files never reach IndexedDB; the
+//! least recently used are evicted when the limit is reached. The storage
+//! directory `/tmp` underneath is hidden while it is mounted.
+//!
+//! Only Init and the PermissionManager may mount, remount (change a memory
+//! mount's size limit) or unmount. Mounts are not checkpointed; after a VFS
+//! restart only `/` and the default `/tmp` are mounted, and memory mounts
+//! lose their contents.
+//!
+//! Hard links, handles, watches and extended attributes are storage
+//! features: `MSG_VFS_LINK`, `MSG_VFS_OPEN`, `MSG_VFS_WATCH` and the
+//! `MSG_VFS_*XATTR` requests on an in-process mount fail with
+//! `NotSupported`, as do snapshots, an unlink into the trash and a rename
+//! between two mounts.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: the request is answered by the backend its path resolves to
+//! - **Forbidden**: Mounting from other processes, changing a read-only
+//!   mount, moving an entry across mounts
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_vfs::ipc::{
+    vfs_msg, ExistsRequest, ExistsResponse, HashRequest, HashResponse, MkdirRequest, MkdirResponse,
+    MountRequest, MountResponse, MountsResponse, ReadFileRequest, ReadFileResponse, ReaddirRequest,
+    ReaddirResponse, RenameRequest, RenameResponse, RmdirRequest, RmdirResponse, StatRequest,
+    StatResponse, UmountRequest, UmountResponse, UnlinkRequest, UnlinkResponse, WriteFileRequest,
+    WriteFileResponse,
+};
+use zos_vfs::storage::integrity::{content_hash, file_hash};
+use zos_vfs::{
+    normalize_path, MountBackend, MountPoint, MountTable, MountedFs, VfsError,
+    DEFAULT_TMP_SIZE_LIMIT, TMP_MOUNT_PATH,
+};
+
+use super::super::{validate_path, ClientContext, VfsService, MAX_CONTENT_SIZE};
+use super::checkpoint::InterruptedResponse;
+
+/// The mount table and the in-process filesystems serving its mounts.
+#[derive(Default)]
+pub struct MountSet {
+    table: MountTable,
+    /// Memory and asset filesystems, by mount path
+    local: BTreeMap<String, MountedFs>,
+}
+
+impl MountSet {
+    /// Mount the backend a request names, or remount an existing one.
+    pub fn mount(&mut self, request: MountRequest) -> Result<(), VfsError> {
+        if request.remount {
+            return self.remount(&request.path, request.size_limit);
+        }
+        let mut mount = MountPoint {
+            path: normalize_path(&request.path)?,
+            backend: request.backend,
+            read_only: request.read_only,
+            size_limit: None,
+        };
+        if request.size_limit.is_some() && mount.backend != MountBackend::Memory {
+            return Err(VfsError::InvalidRequest(String::from(
+                "Only memory mounts take a size limit",
+            )));
+        }
+        let fs = match mount.backend {
+            MountBackend::Storage => None,
+            MountBackend::Memory if mount.read_only => {
+                return Err(VfsError::InvalidRequest(String::from(
+                    "A memory mount cannot be read-only",
+                )))
+            }
+            MountBackend::Memory => {
+                mount.size_limit = request.size_limit;
+                Some(MountedFs::memory(&mount.path, request.size_limit)?)
+            }
+            MountBackend::Assets => {
+                mount.read_only = true;
+                Some(MountedFs::assets(&mount.path, &request.files)?)
+            }
+        };
+        let path = mount.path.clone();
+        self.table.insert(mount)?;
+        if let Some(fs) = fs {
+            self.local.insert(path, fs);
+        }
+        Ok(())
+    }
+
+    /// Change the size limit of the memory mount at `path`, evicting files
+    /// that no longer fit.
+    fn remount(&mut self, path: &str, size_limit: Option<u64>) -> Result<(), VfsError> {
+        let path = normalize_path(path)?;
+        let mount = self.table.get_mut(&path).ok_or(VfsError::NotFound)?;
+        if mount.backend != MountBackend::Memory {
+            return Err(VfsError::InvalidRequest(String::from(
+                "Only memory mounts can be remounted",
+            )));
+        }
+        let fs = self.local.get_mut(&path).ok_or(VfsError::NotFound)?;
+        fs.set_size_limit(size_limit)?;
+        mount.size_limit = size_limit;
+        Ok(())
+    }
+
+    /// Mount `/tmp` on memory with the default size limit.
+    pub fn mount_tmp(&mut self) -> Result<(), VfsError> {
+        self.mount(MountRequest {
+            path: String::from(TMP_MOUNT_PATH),
+            backend: MountBackend::Memory,
+            read_only: false,
+            files: Vec::new(),
+            size_limit: Some(DEFAULT_TMP_SIZE_LIMIT),
+            remount: false,
+        })
+    }
+
+    /// Remove a mount, dropping an in-process filesystem with it.
+    pub fn umount(&mut self, path: &str) -> Result<MountPoint, VfsError> {
+        let mount = self.table.remove(path)?;
+        self.local.remove(&mount.path);
+        Ok(mount)
+    }
+
+    /// The mount serving `path`.
+    pub fn resolve(&self, path: &str) -> &MountPoint {
+        self.table.resolve(path)
+    }
+
+    /// Every mount, sorted by path.
+    pub fn list(&self) -> Vec<MountPoint> {
+        self.table.list().to_vec()
+    }
+}
+
+/// The paths of a request, parsed just far enough to route it.
+///
+/// Unknown fields (including file content) are skipped without being
+/// collected.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RouteFields {
+    path: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    link_path: Option<String>,
+    write: bool,
+    create: bool,
+    trash: bool,
+}
+
+impl RouteFields {
+    /// Parse the fields of a routed request; `None` for other requests or
+    /// if they don't parse.
+    fn parse(tag: u32, data: &[u8]) -> Option<Self> {
+        match tag {
+            vfs_msg::MSG_VFS_MKDIR
+            | vfs_msg::MSG_VFS_RMDIR
+            | vfs_msg::MSG_VFS_READDIR
+            | vfs_msg::MSG_VFS_WRITE
+            | vfs_msg::MSG_VFS_READ
+            | vfs_msg::MSG_VFS_UNLINK
+            | vfs_msg::MSG_VFS_RENAME
+            | vfs_msg::MSG_VFS_LINK
+            | vfs_msg::MSG_VFS_STAT
+            | vfs_msg::MSG_VFS_EXISTS
+            | vfs_msg::MSG_VFS_HASH
+            | vfs_msg::MSG_VFS_OPEN
+            | vfs_msg::MSG_VFS_WATCH
+            | vfs_msg::MSG_VFS_SETXATTR
+            | vfs_msg::MSG_VFS_GETXATTR
+            | vfs_msg::MSG_VFS_LISTXATTR
+            | vfs_msg::MSG_VFS_SNAPSHOT_CREATE => serde_json::from_slice(data).ok(),
+            _ => None,
+        }
+    }
+
+    /// Whether the request would change what its paths name.
+    fn mutates(&self, tag: u32) -> bool {
+        match tag {
+            vfs_msg::MSG_VFS_MKDIR
+            | vfs_msg::MSG_VFS_RMDIR
+            | vfs_msg::MSG_VFS_WRITE
+            | vfs_msg::MSG_VFS_UNLINK
+            | vfs_msg::MSG_VFS_RENAME
+            | vfs_msg::MSG_VFS_LINK
+            | vfs_msg::MSG_VFS_SETXATTR => true,
+            vfs_msg::MSG_VFS_OPEN => self.write || self.create,
+            _ => false,
+        }
+    }
+
+    /// Every path the request names.
+    fn paths(&self) -> Vec<&str> {
+        [&self.path, &self.from, &self.to, &self.link_path]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Paths a filesystem request would change, if it would change any.
+pub fn changed_paths(tag: u32, data: &[u8]) -> Vec<String> {
+    match RouteFields::parse(tag, data) {
+        Some(fields) if fields.mutates(tag) => {
+            fields
+                .paths()
+                .into_iter()
+                .filter_map(|path| normalize_path(path).ok())
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Where a request is served.
+#[derive(Debug)]
+pub enum Route {
+    /// Continue with the storage handlers
+    Storage,
+    /// Serve in-process from the mount at this path
+    Local(String),
+    /// Answer with this error
+    Refuse(VfsError),
+}
+
+/// Route a filesystem request by the mounts its paths resolve to.
+///
+/// Requests that don't parse, or name an invalid path, go to storage,
+/// whose handlers report the problem.
+pub fn route(mounts: &MountSet, tag: u32, data: &[u8]) -> Route {
+    let Some(fields) = RouteFields::parse(tag, data) else {
+        return Route::Storage;
+    };
+    let mutates = fields.mutates(tag);
+    let paths = fields.paths();
+    if paths.is_empty() || paths.iter().any(|p| validate_path(p).is_err()) {
+        return Route::Storage;
+    }
+
+    let resolved: Vec<&MountPoint> = paths.iter().map(|p| mounts.resolve(p)).collect();
+    if mutates && resolved.iter().any(|mount| mount.read_only) {
+        return Route::Refuse(VfsError::PermissionDenied);
+    }
+    if resolved.iter().all(|mount| !mount.is_local()) {
+        return Route::Storage;
+    }
+    if resolved.iter().any(|mount| mount.path != resolved[0].path) {
+        return Route::Refuse(VfsError::NotSupported(String::from(
+            "Cannot move or link entries between mounts",
+        )));
+    }
+    match tag {
+        vfs_msg::MSG_VFS_UNLINK if fields.trash => Route::Refuse(VfsError::NotSupported(format!(
+            "{:?} mounts have no trash",
+            resolved[0].backend
+        ))),
+        vfs_msg::MSG_VFS_LINK
+        | vfs_msg::MSG_VFS_OPEN
+        | vfs_msg::MSG_VFS_WATCH
+        | vfs_msg::MSG_VFS_SETXATTR
+        | vfs_msg::MSG_VFS_GETXATTR
+        | vfs_msg::MSG_VFS_LISTXATTR
+        | vfs_msg::MSG_VFS_SNAPSHOT_CREATE => Route::Refuse(VfsError::NotSupported(format!(
+            "{:?} mounts do not support this operation",
+            resolved[0].backend
+        ))),
+        _ => Route::Local(resolved[0].path.clone()),
+    }
+}
+
+impl VfsService {
+    /// Whether `pid` may change the mount table: Init or the
+    /// PermissionService.
+    fn may_mount(&self, pid: u32) -> bool {
+        pid == zos_process::pid::INIT || self.names.is(pid, zos_process::services::PERMISSION)
+    }
+
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_VFS_MOUNT - mount a backend at a path prefix, or change a
+    /// memory mount's size limit
+    pub fn handle_mount(&mut self, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let result = if !self.may_mount(msg.from_pid) {
+            syscall::debug(&format!(
+                "VfsService: Refusing mount from PID {}",
+                msg.from_pid
+            ));
+            Err(VfsError::PermissionDenied)
+        } else {
+            match serde_json::from_slice::<MountRequest>(&msg.data) {
+                Ok(request) => {
+                    syscall::debug(&format!(
+                        "VfsService: {} {:?} at {} (size limit {:?})",
+                        if request.remount { "remount" } else { "mount" },
+                        request.backend,
+                        request.path,
+                        request.size_limit
+                    ));
+                    self.mounts.mount(request)
+                }
+                Err(e) => Err(VfsError::InvalidRequest(format!(
+                    "Failed to parse request: {}",
+                    e
+                ))),
+            }
+        };
+        self.send_response(
+            &client_ctx,
+            vfs_msg::MSG_VFS_MOUNT_RESPONSE,
+            &MountResponse { result },
+        )
+    }
+
+    /// Handle MSG_VFS_UMOUNT - remove a mount
+    pub fn handle_umount(&mut self, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let result = if !self.may_mount(msg.from_pid) {
+            syscall::debug(&format!(
+                "VfsService: Refusing umount from PID {}",
+                msg.from_pid
+            ));
+            Err(VfsError::PermissionDenied)
+        } else {
+            match serde_json::from_slice::<UmountRequest>(&msg.data) {
+                Ok(request) => {
+                    syscall::debug(&format!("VfsService: umount {}", request.path));
+                    self.mounts.umount(&request.path).map(|_| ())
+                }
+                Err(e) => Err(VfsError::InvalidRequest(format!(
+                    "Failed to parse request: {}",
+                    e
+                ))),
+            }
+        };
+        self.send_response(
+            &client_ctx,
+            vfs_msg::MSG_VFS_UMOUNT_RESPONSE,
+            &UmountResponse { result },
+        )
+    }
+
+    /// Handle MSG_VFS_MOUNTS - list the mount table
+    pub fn handle_mounts(&self, msg: &Message) -> Result<(), AppError> {
+        let response = MountsResponse {
+            result: Ok(self.mounts.list()),
+        };
+        self.send_response(
+            &self.client_context(msg),
+            vfs_msg::MSG_VFS_MOUNTS_RESPONSE,
+            &response,
+        )
+    }
+
+    // =========================================================================
+    // Routing
+    // =========================================================================
+
+    /// Answer a request here if its path isn't served by storage.
+    ///
+    /// Returns `None` if the storage handlers should take it.
+    pub fn route_to_mount(&mut self, msg: &Message) -> Option<Result<(), AppError>> {
+        // Only `/` is mounted: nothing to route
+        if self.mounts.list().len() == 1 {
+            return None;
+        }
+        let client_ctx = self.client_context(msg);
+        match route(&self.mounts, msg.tag, &msg.data) {
+            Route::Storage => None,
+            Route::Refuse(error) => {
+                syscall::debug(&format!(
+                    "VfsService: Refusing tag 0x{:x} from PID {}: {:?}",
+                    msg.tag, msg.from_pid, error
+                ));
+                // Every VFS response tag is the request tag plus one
+                let response = InterruptedResponse { result: Err(error) };
+                Some(self.send_response(&client_ctx, msg.tag + 1, &response))
+            }
+            Route::Local(mount) => Some(self.serve_local(&mount, &client_ctx, msg)),
+        }
+    }
+
+    /// Serve a request from the in-process filesystem mounted at `mount`.
+    fn serve_local(
+        &self,
+        mount: &str,
+        client_ctx: &ClientContext,
+        msg: &Message,
+    ) -> Result<(), AppError> {
+        let Some(fs) = self.mounts.local.get(mount) else {
+            return Ok(());
+        };
+        fs.set_now(syscall::get_wallclock());
+        let invalid = |e: serde_json::Error| {
+            VfsError::InvalidRequest(format!("Failed to parse request: {}", e))
+        };
+        let perm = |path: &str| self.permission_context(msg.from_pid, path);
+        let data = &msg.data;
+
+        match msg.tag {
+            vfs_msg::MSG_VFS_MKDIR => {
+                let result = serde_json::from_slice::<MkdirRequest>(data)
+                    .map_err(invalid)
+                    .and_then(|r| fs.mkdir(&r.path, r.create_parents, &perm(&r.path)));
+                self.send_response(client_ctx, msg.tag + 1, &MkdirResponse { result })
+            }
+            vfs_msg::MSG_VFS_RMDIR => {
+                let result = serde_json::from_slice::<RmdirRequest>(data)
+                    .map_err(invalid)
+                    .and_then(|r| fs.rmdir(&r.path, r.recursive, &perm(&r.path)));
+                self.send_response(client_ctx, msg.tag + 1, &RmdirResponse::new(result))
+            }
+            vfs_msg::MSG_VFS_READDIR => {
+                let result = serde_json::from_slice::<ReaddirRequest>(data)
+                    .map_err(invalid)
+                    .and_then(|r| fs.readdir(&r.path, &perm(&r.path)));
+                self.send_response(client_ctx, msg.tag + 1, &ReaddirResponse { result })
+            }
+            vfs_msg::MSG_VFS_WRITE => {
+                // Content is kept in memory as given; it is never sealed
+                let result = serde_json::from_slice::<WriteFileRequest>(data)
+                    .map_err(invalid)
+                    .and_then(|r| {
+                        if r.content.len() > MAX_CONTENT_SIZE {
+                            return Err(VfsError::FileTooLarge);
+                        }
+                        fs.write_file(&r.path, &r.content, &perm(&r.path))
+                    });
+                self.send_response(client_ctx, msg.tag + 1, &WriteFileResponse { result })
+            }
+            vfs_msg::MSG_VFS_READ => {
+                let result = serde_json::from_slice::<ReadFileRequest>(data)
+                    .map_err(invalid)
+                    .and_then(|r| fs.read_file(&r.path, r.offset, r.length, &perm(&r.path)));
+                self.send_response(client_ctx, msg.tag + 1, &ReadFileResponse { result })
+            }
+            vfs_msg::MSG_VFS_UNLINK => {
+                let result = serde_json::from_slice::<UnlinkRequest>(data)
+                    .map_err(invalid)
+                    .and_then(|r| fs.unlink(&r.path, &perm(&r.path)));
+                self.send_response(client_ctx, msg.tag + 1, &UnlinkResponse { result })
+            }
+            vfs_msg::MSG_VFS_RENAME => {
+                let result = serde_json::from_slice::<RenameRequest>(data)
+                    .map_err(invalid)
+                    .and_then(|r| fs.rename(&r.from, &r.to, &perm(&r.from)));
+                self.send_response(client_ctx, msg.tag + 1, &RenameResponse { result })
+            }
+            vfs_msg::MSG_VFS_STAT => {
+                let result = serde_json::from_slice::<StatRequest>(data)
+                    .map_err(invalid)
+                    .and_then(|r| fs.stat(&r.path));
+                self.send_response(client_ctx, msg.tag + 1, &StatResponse { result })
+            }
+            vfs_msg::MSG_VFS_EXISTS => {
+                let result = serde_json::from_slice::<ExistsRequest>(data)
+                    .map_err(invalid)
+                    .and_then(|r| fs.exists(&r.path));
+                self.send_response(client_ctx, msg.tag + 1, &ExistsResponse { result })
+            }
+            vfs_msg::MSG_VFS_HASH => {
+                // Nothing is recorded for in-process files, so nothing to check
+                let result = serde_json::from_slice::<HashRequest>(data)
+                    .map_err(invalid)
+                    .and_then(|r| fs.read_file(&r.path, None, None, &perm(&r.path)))
+                    .map(|content| file_hash(&content_hash(&content), content.len() as u64));
+                self.send_response(client_ctx, msg.tag + 1, &HashResponse { result })
+            }
+            _ => Ok(()),
+        }
+    }
+}
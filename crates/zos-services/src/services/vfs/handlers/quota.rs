@@ -0,0 +1,214 @@
+//! Per-user storage quotas for VFS Service
+//!
+//! Handles: quota stat, and the usage accounting behind writes and deletes
+//!
+//! A user's usage is the total size of the files they own
+//! (`Inode::owner_id`); directories and files without an owner count
+//! against nobody. A write reserves its size change before any content is
+//! stored and undoes it if the write fails, so a refused or failed write
+//! never moves the counters. Deletes release a file's size once its inode
+//! is gone. Rename keeps the owner and so moves no usage.
+//!
+//! Counters are saved with the service checkpoint, so a crash can lose the
+//! changes since the last one. A write dropped while in flight (cancelled,
+//! or past its deadline) keeps its reservation, since the storage write
+//! may still land.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: usage follows the sizes of committed inodes
+//! - **Acceptable partial failure**: counters lag after a crash
+//! - **Forbidden**: a write that takes its owner past their quota; an
+//!   application reading another user's usage
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_vfs::core::UserId;
+use zos_vfs::ipc::{vfs_msg, QuotaStatRequest, QuotaStatResponse};
+use zos_vfs::{Inode, StorageQuota, VfsError};
+
+use super::super::VfsService;
+
+/// Bytes counted against one user
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Charge {
+    pub user_id: UserId,
+    pub bytes: u64,
+}
+
+impl Charge {
+    /// What a stored inode counts against its owner.
+    pub fn of(inode: &Inode) -> Option<Self> {
+        match inode.owner_id {
+            Some(user_id) if inode.is_file() && inode.size > 0 => Some(Self {
+                user_id,
+                bytes: inode.size,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Usage change a write has applied, undone if the write fails
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Reservation {
+    /// Size of the new file, counted against its owner
+    pub added: Option<Charge>,
+    /// Size of the file it replaces, released from that file's owner
+    pub released: Option<Charge>,
+}
+
+/// Quota and usage of every user that has owned a file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Vec<SavedQuota>", into = "Vec<SavedQuota>")]
+pub struct QuotaTable {
+    users: BTreeMap<UserId, StorageQuota>,
+}
+
+/// Checkpointed form of one user's quota
+///
+/// The user ID is hex: checkpoints are decoded through `serde_json::Value`,
+/// which can't hold a full u128.
+#[derive(Serialize, Deserialize)]
+struct SavedQuota {
+    user_id: String,
+    max_bytes: u64,
+    used_bytes: u64,
+}
+
+impl From<QuotaTable> for Vec<SavedQuota> {
+    fn from(table: QuotaTable) -> Self {
+        table
+            .users
+            .into_values()
+            .map(|quota| SavedQuota {
+                user_id: format!("{:x}", quota.user_id),
+                max_bytes: quota.max_bytes,
+                used_bytes: quota.used_bytes,
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<SavedQuota>> for QuotaTable {
+    fn from(saved: Vec<SavedQuota>) -> Self {
+        let mut users = BTreeMap::new();
+        for entry in saved {
+            // A bad entry only loses that user's count, not the checkpoint
+            let Ok(user_id) = UserId::from_str_radix(&entry.user_id, 16) else {
+                continue;
+            };
+            let mut quota = StorageQuota::with_limit(user_id, entry.max_bytes);
+            quota.update_usage(entry.used_bytes as i64);
+            users.insert(user_id, quota);
+        }
+        Self { users }
+    }
+}
+
+impl QuotaTable {
+    /// `user_id`'s quota, with the default limit if they own nothing yet.
+    pub fn get(&self, user_id: UserId) -> StorageQuota {
+        self.users
+            .get(&user_id)
+            .cloned()
+            .unwrap_or_else(|| StorageQuota::new(user_id))
+    }
+
+    /// Apply a write that stores `added` in place of `released`.
+    ///
+    /// Fails without changing anything if the new owner's usage would
+    /// grow past their quota. A write that doesn't grow it (e.g. one that
+    /// shrinks a file) is let through even when they are already over.
+    pub fn reserve(
+        &mut self,
+        added: Option<Charge>,
+        released: Option<Charge>,
+    ) -> Result<Reservation, VfsError> {
+        if let Some(add) = added {
+            let freed = released
+                .filter(|r| r.user_id == add.user_id)
+                .map_or(0, |r| r.bytes);
+            let growth = add.bytes.saturating_sub(freed);
+            let quota = self.get(add.user_id);
+            if growth > 0 && quota.would_exceed(growth) {
+                return Err(VfsError::UserQuotaExceeded {
+                    used: quota.used_bytes,
+                    limit: quota.max_bytes,
+                    requested: growth,
+                });
+            }
+        }
+        self.release(released);
+        self.adjust(added, 1);
+        Ok(Reservation { added, released })
+    }
+
+    /// Undo a reservation whose write failed.
+    pub fn undo(&mut self, reservation: &Reservation) {
+        self.adjust(reservation.added, -1);
+        self.adjust(reservation.released, 1);
+    }
+
+    /// Release a deleted file's size.
+    pub fn release(&mut self, charge: Option<Charge>) {
+        self.adjust(charge, -1);
+    }
+
+    /// Fold in the counters saved by the previous run.
+    ///
+    /// Requests are served while the checkpoint is being read, so anything
+    /// counted since startup is added on top rather than overwritten.
+    pub fn restore(&mut self, saved: QuotaTable) {
+        let counted = core::mem::replace(&mut self.users, saved.users);
+        for (user_id, quota) in counted {
+            self.users
+                .entry(user_id)
+                .or_insert_with(|| StorageQuota::new(user_id))
+                .update_usage(quota.used_bytes as i64);
+        }
+    }
+
+    fn adjust(&mut self, charge: Option<Charge>, sign: i64) {
+        if let Some(charge) = charge {
+            self.users
+                .entry(charge.user_id)
+                .or_insert_with(|| StorageQuota::new(charge.user_id))
+                .update_usage(sign * charge.bytes as i64);
+        }
+    }
+}
+
+impl VfsService {
+    /// Handle MSG_VFS_QUOTA_STAT - report a user's quota and usage
+    ///
+    /// Usage figures aren't secret (like `df`), so any process may ask
+    /// about any user.
+    pub fn handle_quota_stat(&mut self, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let result = match serde_json::from_slice::<QuotaStatRequest>(&msg.data) {
+            // Applications may only see the usage of the user they act as
+            Ok(request)
+                if !self.is_system_caller(msg.from_pid)
+                    && self.callers.user() != Some(request.user_id) =>
+            {
+                Err(VfsError::PermissionDenied)
+            }
+            Ok(request) => Ok(self.quotas.get(request.user_id)),
+            Err(e) => {
+                syscall::debug(&format!("VfsService: bad quota stat request: {}", e));
+                Err(VfsError::InvalidRequest(format!(
+                    "Failed to parse request: {}",
+                    e
+                )))
+            }
+        };
+        let response = QuotaStatResponse { result };
+        self.send_response(&client_ctx, vfs_msg::MSG_VFS_QUOTA_STAT_RESPONSE, &response)
+    }
+}
@@ -14,19 +14,23 @@ use alloc::vec::Vec;
 use alloc::string::ToString;
 use zos_apps::syscall;
 use zos_apps::{AppContext, AppError, Message};
-use zos_process::storage_result;
+use zos_ipc::storage::ResultKind;
 use zos_vfs::ipc::{
     vfs_msg, ExistsRequest, ExistsResponse, ReadFileRequest, ReadFileResponse, ReaddirRequest,
     ReaddirResponse, StatRequest, StatResponse,
 };
 use zos_vfs::service::{check_read, PermissionContext};
-use zos_vfs::{DirEntry, Inode};
+use zos_vfs::storage::chunking::is_manifest;
+use zos_vfs::storage::integrity::ContentHash;
+use zos_vfs::DirEntry;
 use zos_vfs::VfsError;
 
 use super::super::{
-    content_key, derive_permission_context, inode_key, result_type_name, validate_path,
-    ClientContext, InodeOpType, PendingOp, ReaddirStage, VfsService,
+    content_key, inode_key, parse_inode, validate_path, ClientContext, InodeOpType, PendingOp,
+    ReaddirStage, VfsService,
 };
+use super::chunks::ChunkReadReply;
+use super::hash::checked_content;
 
 impl VfsService {
     // =========================================================================
@@ -64,8 +68,8 @@ impl VfsService {
         syscall::debug(&format!("VfsService: stat {}", request.path));
 
         // Derive permission context from caller
-        let perm_ctx = derive_permission_context(msg.from_pid, &request.path);
-        let client_ctx = ClientContext::from_message(msg);
+        let perm_ctx = self.permission_context(msg.from_pid, &request.path);
+        let client_ctx = self.client_context(msg);
 
         // Start async inode read
         self.start_storage_read(
@@ -110,7 +114,7 @@ impl VfsService {
 
         syscall::debug(&format!("VfsService: exists {}", request.path));
 
-        let client_ctx = ClientContext::from_message(msg);
+        let client_ctx = self.client_context(msg);
 
         // Start async exists check (no permission check needed for exists)
         self.start_storage_exists(
@@ -153,8 +157,8 @@ impl VfsService {
         syscall::debug(&format!("VfsService: read {}", request.path));
 
         // Derive permission context from caller
-        let perm_ctx = derive_permission_context(msg.from_pid, &request.path);
-        let client_ctx = ClientContext::from_message(msg);
+        let perm_ctx = self.permission_context(msg.from_pid, &request.path);
+        let client_ctx = self.client_context(msg);
 
         // First check inode exists and is a file
         self.start_storage_read(
@@ -199,8 +203,8 @@ impl VfsService {
         syscall::debug(&format!("VfsService: readdir {}", request.path));
 
         // Derive permission context from caller
-        let perm_ctx = derive_permission_context(msg.from_pid, &request.path);
-        let client_ctx = ClientContext::from_message(msg);
+        let perm_ctx = self.permission_context(msg.from_pid, &request.path);
+        let client_ctx = self.client_context(msg);
 
         // First read directory inode to check permissions
         self.start_storage_read(
@@ -223,12 +227,12 @@ impl VfsService {
         &self,
         client_ctx: &ClientContext,
         perm_ctx: &PermissionContext,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         let response = match result_type {
-            storage_result::READ_OK => {
-                match serde_json::from_slice::<Inode>(data) {
+            ResultKind::ReadOk => {
+                match parse_inode(data) {
                     Ok(inode) => {
                         // Check read permission before returning inode info
                         if !check_read(&inode, perm_ctx) {
@@ -251,20 +255,20 @@ impl VfsService {
                     },
                 }
             }
-            storage_result::NOT_FOUND => StatResponse {
+            ResultKind::NotFound => StatResponse {
                 result: Err(VfsError::NotFound),
             },
             _ => {
                 syscall::debug(&format!(
                     "VfsService: stat failed with unexpected result: {} ({})",
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 StatResponse {
                     result: Err(VfsError::StorageError(format!(
                         "Inode read failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     ))),
                 }
             }
@@ -276,9 +280,9 @@ impl VfsService {
     pub fn handle_exists_inode_result(
         &self,
         client_ctx: &ClientContext,
-        result_type: u8,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
-        let exists = result_type == storage_result::READ_OK;
+        let exists = result_type == ResultKind::ReadOk;
         let response = ExistsResponse { result: Ok(exists) };
         self.send_response(client_ctx, vfs_msg::MSG_VFS_EXISTS_RESPONSE, &response)
     }
@@ -289,11 +293,11 @@ impl VfsService {
         client_ctx: &ClientContext,
         path: &str,
         perm_ctx: &PermissionContext,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
-        if result_type == storage_result::READ_OK {
-            match serde_json::from_slice::<Inode>(data) {
+        if result_type == ResultKind::ReadOk {
+            match parse_inode(data) {
                 Ok(inode) if inode.is_file() => {
                     // Check read permission before fetching content
                     if !check_read(&inode, perm_ctx) {
@@ -317,6 +321,7 @@ impl VfsService {
                             ctx: client_ctx.clone(),
                             path: path.to_string(),
                             perm_ctx: perm_ctx.clone(),
+                            expected_hash: self.read_expectation(inode.content_hash),
                         },
                     )
                 }
@@ -341,7 +346,7 @@ impl VfsService {
                     )
                 }
             }
-        } else if result_type == storage_result::NOT_FOUND {
+        } else if result_type == ResultKind::NotFound {
             let response = ReadFileResponse {
                 result: Err(VfsError::NotFound),
             };
@@ -358,19 +363,24 @@ impl VfsService {
 
     /// Handle content read result
     pub fn handle_content_result(
-        &self,
+        &mut self,
         client_ctx: &ClientContext,
         path: &str,
-        result_type: u8,
+        expected: Option<ContentHash>,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
+        if result_type == ResultKind::ReadOk && is_manifest(data) {
+            return self.start_chunk_read(client_ctx, data, ChunkReadReply::Read { expected });
+        }
         let response = match result_type {
-            storage_result::READ_OK => {
-                ReadFileResponse {
-                    result: Ok(data.to_vec()),
-                }
-            }
-            storage_result::NOT_FOUND => {
+            ResultKind::ReadOk => ReadFileResponse {
+                result: self
+                    .content_keys
+                    .open(data)
+                    .and_then(|content| checked_content(expected.as_ref(), content)),
+            },
+            ResultKind::NotFound => {
                 // Rule 5: If inode exists but content is missing, this is a storage inconsistency
                 // not an empty file. Return an error to surface the corruption.
                 syscall::debug(&format!(
@@ -387,14 +397,14 @@ impl VfsService {
                 syscall::debug(&format!(
                     "VfsService: read {} content fetch failed: {} ({})",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 ReadFileResponse {
                     result: Err(VfsError::StorageError(format!(
                         "Content read failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     ))),
                 }
             }
@@ -409,11 +419,11 @@ impl VfsService {
     pub fn handle_list_children_result(
         &self,
         client_ctx: &ClientContext,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         let response = match result_type {
-            storage_result::LIST_OK => {
+            ResultKind::ListOk => {
                 // data is JSON array of keys
                 match serde_json::from_slice::<Vec<String>>(data) {
                     Ok(keys) => {
@@ -441,7 +451,7 @@ impl VfsService {
                     },
                 }
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 // Rule 5: NOT_FOUND on list means directory doesn't exist or has no children
                 // Since we should have checked existence first via ReaddirOp, this is unexpected
                 ReaddirResponse {
@@ -453,8 +463,8 @@ impl VfsService {
                 ReaddirResponse {
                     result: Err(VfsError::StorageError(format!(
                         "List failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     ))),
                 }
             }
@@ -466,22 +476,22 @@ impl VfsService {
     pub fn handle_exists_result(
         &self,
         client_ctx: &ClientContext,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         let response = match result_type {
-            storage_result::EXISTS_OK => {
+            ResultKind::ExistsOk => {
                 let exists = !data.is_empty() && data[0] == 1;
                 ExistsResponse { result: Ok(exists) }
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 ExistsResponse { result: Ok(false) }
             }
             _ => {
                 ExistsResponse {
                     result: Err(VfsError::StorageError(format!(
                         "Exists check failed: unexpected result type {}",
-                        result_type
+                        result_type.name()
                     ))),
                 }
             }
@@ -504,7 +514,7 @@ impl VfsService {
         path: &str,
         perm_ctx: &PermissionContext,
         stage: ReaddirStage,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         match stage {
@@ -523,15 +533,15 @@ impl VfsService {
         client_ctx: &ClientContext,
         path: &str,
         perm_ctx: &PermissionContext,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         // Handle result type strictly
         match result_type {
-            storage_result::READ_OK => {
+            ResultKind::ReadOk => {
                 // Good - parse and validate
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 let response = ReaddirResponse {
                     result: Err(VfsError::NotFound),
                 };
@@ -541,14 +551,14 @@ impl VfsService {
                 syscall::debug(&format!(
                     "VfsService: readdir {} inode read failed: {} ({})",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 let response = ReaddirResponse {
                     result: Err(VfsError::StorageError(format!(
                         "Inode read failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     ))),
                 };
                 return self.send_response(client_ctx, vfs_msg::MSG_VFS_READDIR_RESPONSE, &response);
@@ -556,7 +566,7 @@ impl VfsService {
         }
 
         // Parse inode - FAIL CLOSED on parse error
-        let inode = match serde_json::from_slice::<Inode>(data) {
+        let inode = match parse_inode(data) {
             Ok(inode) => inode,
             Err(e) => {
                 syscall::debug(&format!(
@@ -613,11 +623,11 @@ impl VfsService {
     fn handle_readdir_listing_children(
         &self,
         client_ctx: &ClientContext,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         let response = match result_type {
-            storage_result::LIST_OK => {
+            ResultKind::ListOk => {
                 // data is JSON array of keys
                 match serde_json::from_slice::<Vec<String>>(data) {
                     Ok(keys) => {
@@ -645,7 +655,7 @@ impl VfsService {
                     },
                 }
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 // No children (empty directory)
                 ReaddirResponse {
                     result: Ok(Vec::new()),
@@ -656,8 +666,8 @@ impl VfsService {
                 ReaddirResponse {
                     result: Err(VfsError::StorageError(format!(
                         "List failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     ))),
                 }
             }
@@ -0,0 +1,872 @@
+//! Rename operation handlers for VFS Service
+//!
+//! Handles: rename (including moves across directories)
+//!
+//! A rename moves the entry's inode (and content, for files) to the keys
+//! of the new path with an updated parent and name; the bytes are never
+//! sent back to the client. A directory's children are stored under keys
+//! derived from its path, so a directory takes every descendant with it:
+//! all are copied under the new path before any old one is removed, and a
+//! failed copy is rolled back (see [`MoveWalk`]).
+//!
+//! Moving an entry to the trash and restoring it run this same state
+//! machine (see `handlers::trash`); the trash marks are applied to the inode
+//! as soon as it is read, so they land with it under the new path.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: new content and inodes written, old inodes and content
+//!   deleted
+//! - **Acceptable partial failure**: orphan content at either path, or
+//!   entries visible at both paths if old inodes can't be deleted
+//! - **Forbidden**: an entry visible at neither path (new entries are
+//!   committed before the old ones are removed)
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, Message};
+use zos_ipc::storage::ResultKind;
+use zos_vfs::core::extract_user_id;
+use zos_vfs::core::is_under;
+use zos_vfs::ipc::{
+    vfs_msg, RenameRequest, RenameResponse, TrashRestoreResponse, UnlinkResponse, VfsEventKind,
+};
+use zos_vfs::service::{check_write, PermissionContext};
+use zos_vfs::trash::{self, TRASH_DIR_NAME};
+use zos_vfs::{parent_path, Inode, VfsError};
+
+use super::super::{
+    content_key, inode_key, parse_inode, validate_path, ClientContext, MoveStep, MoveWalk,
+    MovedEntry, PendingOp, RenameReply, RenameStage, SubtreeStage, VfsService,
+};
+use super::storage_failure;
+
+/// Where `path`, at or under `from`, lands when `from` moves to `to`.
+fn moved_path(from: &str, to: &str, path: &str) -> String {
+    format!("{}{}", to, &path[from.len()..])
+}
+
+/// Apply what the request behind a move needs to the source inode.
+///
+/// An entry going to the trash is marked with where it came from; one
+/// coming back loses the marks and, without a target of its own, goes back
+/// there.
+fn prepare_source(
+    reply: &RenameReply,
+    from: &str,
+    to: &mut String,
+    inode: &mut Inode,
+) -> Result<(), VfsError> {
+    match reply {
+        RenameReply::Rename => Ok(()),
+        RenameReply::Trash { deleted_at } => trash::mark_trashed(inode, from, *deleted_at),
+        RenameReply::Restore => {
+            if to.is_empty() {
+                *to = trash::original_path(inode)
+                    .ok_or_else(|| {
+                        VfsError::InvalidRequest(
+                            "Not a trashed file; give a path to restore to".into(),
+                        )
+                    })?
+                    .to_string();
+                validate_path(to).map_err(|reason| VfsError::InvalidPath(reason.into()))?;
+            }
+            trash::unmark_trashed(inode);
+            Ok(())
+        }
+    }?;
+    // The trash or a restore target may lie inside the directory moving
+    if from != to && is_under(to, from) {
+        return Err(VfsError::InvalidPath(
+            "Cannot move a directory into itself".into(),
+        ));
+    }
+    Ok(())
+}
+
+impl VfsService {
+    /// Send the error response for the request behind a move.
+    fn send_rename_error(
+        &self,
+        client_ctx: &ClientContext,
+        reply: &RenameReply,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        let tag = reply.response_tag();
+        match reply {
+            RenameReply::Rename => {
+                self.send_response(client_ctx, tag, &RenameResponse { result: Err(error) })
+            }
+            RenameReply::Trash { .. } => {
+                self.send_response(client_ctx, tag, &UnlinkResponse { result: Err(error) })
+            }
+            RenameReply::Restore => self.send_response(
+                client_ctx,
+                tag,
+                &TrashRestoreResponse { result: Err(error) },
+            ),
+        }
+    }
+
+    /// Handle MSG_VFS_RENAME - move a file or directory
+    ///
+    /// This starts the rename state machine:
+    /// 1. Read source inode, check it's writable
+    /// 2. Check target doesn't exist
+    /// 3. Check target parent is a writable directory
+    /// 4. Files only: read content and write it under the new path
+    /// 5. Write inode under the new path
+    /// 6. Directories only: move the descendants
+    /// 7. Delete old inode, then old content
+    pub fn handle_rename(&mut self, _ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
+        let request: RenameRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(e) => {
+                let response = RenameResponse {
+                    result: Err(VfsError::InvalidRequest(format!(
+                        "Failed to parse request: {}",
+                        e
+                    ))),
+                };
+                return self.send_response_via_debug(
+                    msg.from_pid,
+                    vfs_msg::MSG_VFS_RENAME_RESPONSE,
+                    &response,
+                );
+            }
+        };
+
+        let invalid = if let Err(reason) = validate_path(&request.from) {
+            Some(VfsError::InvalidPath(String::from(reason)))
+        } else if let Err(reason) = validate_path(&request.to) {
+            Some(VfsError::InvalidPath(String::from(reason)))
+        } else if request.from == "/" || request.to == "/" {
+            Some(VfsError::InvalidPath("Cannot rename root".into()))
+        } else if request.from != request.to && is_under(&request.to, &request.from) {
+            Some(VfsError::InvalidPath(
+                "Cannot move a directory into itself".into(),
+            ))
+        } else {
+            None
+        };
+        if let Some(error) = invalid {
+            let response = RenameResponse { result: Err(error) };
+            return self.send_response_via_debug(
+                msg.from_pid,
+                vfs_msg::MSG_VFS_RENAME_RESPONSE,
+                &response,
+            );
+        }
+
+        syscall::debug(&format!(
+            "VfsService: rename {} -> {}",
+            request.from, request.to
+        ));
+
+        let perm_ctx = self.permission_context(msg.from_pid, &request.from);
+        let client_ctx = self.client_context(msg);
+
+        self.start_storage_read(
+            &inode_key(&request.from),
+            PendingOp::RenameOp {
+                ctx: client_ctx,
+                from: request.from,
+                to: request.to,
+                perm_ctx,
+                stage: RenameStage::ReadingSource,
+                reply: RenameReply::Rename,
+            },
+        )
+    }
+
+    /// Handle rename operation result - dispatches based on stage
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle_rename_op_result(
+        &mut self,
+        client_ctx: ClientContext,
+        from: String,
+        mut to: String,
+        perm_ctx: PermissionContext,
+        stage: RenameStage,
+        reply: RenameReply,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let next = match stage {
+            RenameStage::ReadingSource => self
+                .rename_reading_source(&client_ctx, &from, &perm_ctx, result_type, data)
+                .and_then(|mut inode| {
+                    prepare_source(&reply, &from, &mut to, &mut inode)?;
+                    Ok((inode_key(&to), RenameStage::CheckingTarget { inode }))
+                }),
+            RenameStage::CheckingTarget { inode } => match result_type {
+                ResultKind::NotFound => Ok((
+                    inode_key(&parent_path(&to)),
+                    RenameStage::CheckingParent { inode },
+                )),
+                // Renaming onto itself is a no-op
+                ResultKind::ReadOk if from == to => {
+                    return self.finish_rename(&client_ctx, &from, &to, &reply)
+                }
+                ResultKind::ReadOk => Err(VfsError::AlreadyExists),
+                _ => Err(storage_failure("Target check", result_type)),
+            },
+            // The first file a user trashes creates their trash directory
+            RenameStage::CheckingParent { inode }
+                if result_type == ResultKind::NotFound
+                    && matches!(reply, RenameReply::Trash { .. }) =>
+            {
+                return self.rename_create_trash_dir(client_ctx, from, to, perm_ctx, reply, inode);
+            }
+            RenameStage::CheckingParent { inode } => {
+                match self.rename_checking_parent(&client_ctx, &from, &perm_ctx, result_type, data)
+                {
+                    Ok(()) if inode.is_file() => {
+                        Ok((content_key(&from), RenameStage::ReadingContent { inode }))
+                    }
+                    // Directories have no content to move
+                    Ok(()) => {
+                        return self
+                            .rename_write_inode(client_ctx, from, to, perm_ctx, reply, inode)
+                    }
+                    Err(error) => Err(error),
+                }
+            }
+            RenameStage::CreatingTrashDir { inode } => match result_type {
+                ResultKind::WriteOk if inode.is_file() => {
+                    Ok((content_key(&from), RenameStage::ReadingContent { inode }))
+                }
+                ResultKind::WriteOk => {
+                    return self.rename_write_inode(client_ctx, from, to, perm_ctx, reply, inode)
+                }
+                _ => Err(storage_failure("Trash directory write", result_type)),
+            },
+            RenameStage::ReadingContent { inode } => match result_type {
+                ResultKind::ReadOk => {
+                    return self.start_storage_write(
+                        &content_key(&to),
+                        data,
+                        PendingOp::RenameOp {
+                            ctx: client_ctx,
+                            from,
+                            to,
+                            perm_ctx,
+                            stage: RenameStage::WritingContent { inode },
+                            reply,
+                        },
+                    );
+                }
+                ResultKind::NotFound => Err(VfsError::StorageError(
+                    "Content missing for existing inode".into(),
+                )),
+                _ => Err(storage_failure("Content read", result_type)),
+            },
+            RenameStage::WritingContent { inode } => match result_type {
+                ResultKind::WriteOk => {
+                    return self.rename_write_inode(client_ctx, from, to, perm_ctx, reply, inode);
+                }
+                _ => Err(storage_failure("Content write", result_type)),
+            },
+            RenameStage::WritingInode { is_file } => match result_type {
+                // A directory's descendants follow it before anything old goes
+                ResultKind::WriteOk if !is_file => {
+                    let walk = MoveWalk {
+                        steps: alloc::vec![MoveStep::List(from.clone())],
+                        ..MoveWalk::default()
+                    };
+                    return self.subtree_next_step(client_ctx, from, to, perm_ctx, reply, walk);
+                }
+                // The entry now exists at both paths; drop the old inode first
+                // so the old path never points at deleted content
+                ResultKind::WriteOk => {
+                    return self.start_storage_delete(
+                        &inode_key(&from),
+                        PendingOp::RenameOp {
+                            ctx: client_ctx,
+                            from,
+                            to,
+                            perm_ctx,
+                            stage: RenameStage::DeletingSourceInode { is_file },
+                            reply,
+                        },
+                    );
+                }
+                _ => Err(storage_failure("Inode write", result_type)),
+            },
+            RenameStage::DeletingSourceInode { is_file } => match result_type {
+                ResultKind::WriteOk | ResultKind::NotFound if is_file => {
+                    return self.start_storage_delete(
+                        &content_key(&from),
+                        PendingOp::RenameOp {
+                            ctx: client_ctx,
+                            from,
+                            to,
+                            perm_ctx,
+                            stage: RenameStage::DeletingSourceContent,
+                            reply,
+                        },
+                    );
+                }
+                ResultKind::WriteOk | ResultKind::NotFound => {
+                    return self.finish_rename(&client_ctx, &from, &to, &reply);
+                }
+                _ => {
+                    syscall::debug(&format!(
+                        "VfsService: rename {} -> {} left the entry at both paths",
+                        from, to
+                    ));
+                    // The copy at the new path is real, so watchers see it
+                    self.notify_watchers(VfsEventKind::Create, &to, None);
+                    Err(storage_failure("Old inode delete", result_type))
+                }
+            },
+            RenameStage::DeletingSourceContent => {
+                if !matches!(result_type, ResultKind::WriteOk | ResultKind::NotFound) {
+                    // The rename itself is complete; the old content is orphaned
+                    syscall::debug(&format!(
+                        "VfsService: rename {} old content delete failed: {} ({}) - content is orphaned",
+                        from,
+                        result_type as u8,
+                        result_type.name()
+                    ));
+                }
+                return self.finish_rename(&client_ctx, &from, &to, &reply);
+            }
+            RenameStage::MovingSubtree { walk, step } => {
+                return self.handle_subtree_result(
+                    client_ctx,
+                    from,
+                    to,
+                    perm_ctx,
+                    reply,
+                    walk,
+                    step,
+                    result_type,
+                    data,
+                );
+            }
+        };
+
+        match next {
+            Ok((key, stage)) => self.start_storage_read(
+                &key,
+                PendingOp::RenameOp {
+                    ctx: client_ctx,
+                    from,
+                    to,
+                    perm_ctx,
+                    stage,
+                    reply,
+                },
+            ),
+            Err(error) => {
+                syscall::debug(&format!(
+                    "VfsService: rename {} -> {} failed: {:?}",
+                    from, to, error
+                ));
+                self.send_rename_error(&client_ctx, &reply, error)
+            }
+        }
+    }
+
+    /// Stage 1: Source inode read - check permissions
+    fn rename_reading_source(
+        &self,
+        client_ctx: &ClientContext,
+        from: &str,
+        perm_ctx: &PermissionContext,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<Box<Inode>, VfsError> {
+        match result_type {
+            ResultKind::ReadOk => {}
+            ResultKind::NotFound => return Err(VfsError::NotFound),
+            _ => return Err(storage_failure("Inode read", result_type)),
+        }
+
+        // FAIL CLOSED on parse error
+        let inode = parse_inode(data)
+            .map(Box::new)
+            .map_err(|e| VfsError::StorageError(format!("Failed to parse inode: {}", e)))?;
+
+        if !check_write(&inode, perm_ctx) {
+            syscall::debug(&format!(
+                "VfsService: Permission denied for rename {} (pid={})",
+                from, client_ctx.pid
+            ));
+            return Err(VfsError::PermissionDenied);
+        }
+
+        Ok(inode)
+    }
+
+    /// Stage 4: Target parent read - must be a directory we can write to
+    fn rename_checking_parent(
+        &self,
+        client_ctx: &ClientContext,
+        from: &str,
+        perm_ctx: &PermissionContext,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), VfsError> {
+        match result_type {
+            ResultKind::ReadOk => {}
+            ResultKind::NotFound => return Err(VfsError::NotFound),
+            _ => return Err(storage_failure("Parent read", result_type)),
+        }
+
+        // SECURITY: Fail closed - a corrupt parent must not bypass the check
+        let parent = parse_inode(data).map_err(|e| {
+            VfsError::StorageError(format!("Parent inode corrupt or invalid: {}", e))
+        })?;
+        if !parent.is_directory() {
+            return Err(VfsError::NotADirectory);
+        }
+        if !check_write(&parent, perm_ctx) {
+            syscall::debug(&format!(
+                "VfsService: Permission denied for rename {} into {} (pid={})",
+                from, parent.path, client_ctx.pid
+            ));
+            return Err(VfsError::PermissionDenied);
+        }
+
+        Ok(())
+    }
+
+    /// Create the trash directory a file is being moved to, owned by the
+    /// user whose trash it is.
+    fn rename_create_trash_dir(
+        &mut self,
+        client_ctx: ClientContext,
+        from: String,
+        to: String,
+        perm_ctx: PermissionContext,
+        reply: RenameReply,
+        inode: Box<Inode>,
+    ) -> Result<(), AppError> {
+        let dir = parent_path(&to);
+        let dir_inode = Inode::new_directory(
+            dir.clone(),
+            parent_path(&dir),
+            TRASH_DIR_NAME.to_string(),
+            extract_user_id(&dir),
+            syscall::get_wallclock(),
+        );
+        let inode_json = match serde_json::to_vec(&dir_inode) {
+            Ok(j) => j,
+            Err(e) => {
+                return self.send_rename_error(
+                    &client_ctx,
+                    &reply,
+                    VfsError::StorageError(format!("Failed to serialize inode: {}", e)),
+                );
+            }
+        };
+        syscall::debug(&format!("VfsService: creating trash directory {}", dir));
+        self.start_storage_write(
+            &inode_key(&dir),
+            &inode_json,
+            PendingOp::RenameOp {
+                ctx: client_ctx,
+                from,
+                to,
+                perm_ctx,
+                stage: RenameStage::CreatingTrashDir { inode },
+                reply,
+            },
+        )
+    }
+
+    /// Write the moved inode under its new path.
+    fn rename_write_inode(
+        &mut self,
+        client_ctx: ClientContext,
+        from: String,
+        to: String,
+        perm_ctx: PermissionContext,
+        reply: RenameReply,
+        mut inode: Box<Inode>,
+    ) -> Result<(), AppError> {
+        let is_file = inode.is_file();
+        inode.path = to.clone();
+        inode.parent_path = parent_path(&to);
+        inode.name = to.rsplit('/').next().unwrap_or(&to).to_string();
+        inode.modified_at = syscall::get_wallclock();
+
+        let inode_json = match serde_json::to_vec(&inode) {
+            Ok(j) => j,
+            Err(e) => {
+                return self.send_rename_error(
+                    &client_ctx,
+                    &reply,
+                    VfsError::StorageError(format!("Failed to serialize inode: {}", e)),
+                );
+            }
+        };
+
+        self.start_storage_write(
+            &inode_key(&to),
+            &inode_json,
+            PendingOp::RenameOp {
+                ctx: client_ctx,
+                from,
+                to,
+                perm_ctx,
+                stage: RenameStage::WritingInode { is_file },
+                reply,
+            },
+        )
+    }
+
+    // =========================================================================
+    // Subtree move (directories)
+    // =========================================================================
+
+    /// Handle a storage result for a directory's descendants
+    ///
+    /// Each result updates the walk, then the next step is started:
+    /// 1. Listing: queue the children to copy
+    /// 2. ReadingInode: files go on to their content, directories straight
+    ///    to their inode
+    /// 3. ReadingContent / WritingContent: the content record names no
+    ///    path, so it is written under the new one as it is
+    /// 4. WritingInode: directories queue a listing of their own children
+    /// 5. Deleting: inode, then content; a failure removing an old entry
+    ///    ends the move with the tree at both paths
+    #[allow(clippy::too_many_arguments)]
+    fn handle_subtree_result(
+        &mut self,
+        client_ctx: ClientContext,
+        from: String,
+        to: String,
+        perm_ctx: PermissionContext,
+        reply: RenameReply,
+        mut walk: MoveWalk,
+        step: SubtreeStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        match step {
+            SubtreeStage::Listing { dir } => match result_type {
+                ResultKind::ListOk => match serde_json::from_slice::<Vec<String>>(data) {
+                    Ok(children) => walk.steps.extend(children.into_iter().map(MoveStep::Copy)),
+                    Err(e) => walk.fail(
+                        &dir,
+                        VfsError::StorageError(format!("Failed to parse child list: {}", e)),
+                    ),
+                },
+                ResultKind::NotFound => {}
+                _ => walk.fail(&dir, storage_failure("Child list", result_type)),
+            },
+            SubtreeStage::ReadingInode { path } => match result_type {
+                ResultKind::ReadOk => match parse_inode(data) {
+                    Ok(inode) if inode.is_file() => {
+                        let step = SubtreeStage::ReadingContent {
+                            path: path.clone(),
+                            inode: Box::new(inode),
+                        };
+                        return self.start_storage_read(
+                            &content_key(&path),
+                            PendingOp::RenameOp {
+                                ctx: client_ctx,
+                                from,
+                                to,
+                                perm_ctx,
+                                stage: RenameStage::MovingSubtree { walk, step },
+                                reply,
+                            },
+                        );
+                    }
+                    Ok(inode) => {
+                        return self.subtree_write_inode(
+                            client_ctx,
+                            from,
+                            to,
+                            perm_ctx,
+                            reply,
+                            walk,
+                            path,
+                            Box::new(inode),
+                        )
+                    }
+                    Err(e) => walk.fail(
+                        &path,
+                        VfsError::StorageError(format!("Failed to parse inode: {}", e)),
+                    ),
+                },
+                // Removed since the listing
+                ResultKind::NotFound => {}
+                _ => walk.fail(&path, storage_failure("Inode read", result_type)),
+            },
+            SubtreeStage::ReadingContent { path, inode } => match result_type {
+                ResultKind::ReadOk => {
+                    let key = content_key(&moved_path(&from, &to, &path));
+                    let step = SubtreeStage::WritingContent { path, inode };
+                    return self.start_storage_write(
+                        &key,
+                        data,
+                        PendingOp::RenameOp {
+                            ctx: client_ctx,
+                            from,
+                            to,
+                            perm_ctx,
+                            stage: RenameStage::MovingSubtree { walk, step },
+                            reply,
+                        },
+                    );
+                }
+                ResultKind::NotFound => walk.fail(
+                    &path,
+                    VfsError::StorageError("Content missing for existing inode".into()),
+                ),
+                _ => walk.fail(&path, storage_failure("Content read", result_type)),
+            },
+            SubtreeStage::WritingContent { path, inode } => match result_type {
+                ResultKind::WriteOk => {
+                    // Undone from here on, whether or not the inode follows
+                    walk.copied.push(MovedEntry {
+                        path: path.clone(),
+                        is_file: true,
+                    });
+                    return self.subtree_write_inode(
+                        client_ctx, from, to, perm_ctx, reply, walk, path, inode,
+                    );
+                }
+                _ => walk.fail(&path, storage_failure("Content write", result_type)),
+            },
+            SubtreeStage::WritingInode { path, is_file } => match result_type {
+                ResultKind::WriteOk if !is_file => {
+                    walk.copied.push(MovedEntry {
+                        path: path.clone(),
+                        is_file: false,
+                    });
+                    walk.steps.push(MoveStep::List(path));
+                }
+                ResultKind::WriteOk => {}
+                _ => walk.fail(&path, storage_failure("Inode write", result_type)),
+            },
+            SubtreeStage::Deleting { entry, content } => {
+                let undoing = walk.error.is_some();
+                let path = if undoing {
+                    moved_path(&from, &to, &entry.path)
+                } else {
+                    entry.path.clone()
+                };
+                match result_type {
+                    ResultKind::WriteOk | ResultKind::NotFound if entry.is_file && !content => {
+                        let step = SubtreeStage::Deleting {
+                            entry,
+                            content: true,
+                        };
+                        return self.start_storage_delete(
+                            &content_key(&path),
+                            PendingOp::RenameOp {
+                                ctx: client_ctx,
+                                from,
+                                to,
+                                perm_ctx,
+                                stage: RenameStage::MovingSubtree { walk, step },
+                                reply,
+                            },
+                        );
+                    }
+                    ResultKind::WriteOk | ResultKind::NotFound => {}
+                    // Old entries above it must stay, so stop here
+                    _ if !undoing && !content => {
+                        syscall::debug(&format!(
+                            "VfsService: rename {} -> {} left entries at both paths",
+                            from, to
+                        ));
+                        self.notify_watchers(VfsEventKind::Create, &to, None);
+                        let error = storage_failure("Old inode delete", result_type);
+                        return self.send_rename_error(&client_ctx, &reply, error);
+                    }
+                    _ => syscall::debug(&format!(
+                        "VfsService: rename {} delete failed: {} ({}) - left behind",
+                        path,
+                        result_type as u8,
+                        result_type.name()
+                    )),
+                }
+            }
+            SubtreeStage::DeletingRoot => {
+                if !matches!(result_type, ResultKind::WriteOk | ResultKind::NotFound) {
+                    syscall::debug(&format!(
+                        "VfsService: rename {} -> {} could not remove {}: {} ({})",
+                        from,
+                        to,
+                        to,
+                        result_type as u8,
+                        result_type.name()
+                    ));
+                }
+                let error = walk
+                    .error
+                    .take()
+                    .unwrap_or_else(|| VfsError::StorageError("Move undone".into()));
+                syscall::debug(&format!(
+                    "VfsService: rename {} -> {} failed: {:?}",
+                    from, to, error
+                ));
+                return self.send_rename_error(&client_ctx, &reply, error);
+            }
+        }
+
+        self.subtree_next_step(client_ctx, from, to, perm_ctx, reply, walk)
+    }
+
+    /// Start the next step of a subtree move.
+    fn subtree_next_step(
+        &mut self,
+        client_ctx: ClientContext,
+        from: String,
+        to: String,
+        perm_ctx: PermissionContext,
+        reply: RenameReply,
+        mut walk: MoveWalk,
+    ) -> Result<(), AppError> {
+        let (key, step) = if walk.error.is_some() {
+            // Take back the copies, then the directory's own new inode
+            match walk.copied.pop() {
+                Some(entry) => (
+                    inode_key(&moved_path(&from, &to, &entry.path)),
+                    SubtreeStage::Deleting {
+                        entry,
+                        content: false,
+                    },
+                ),
+                None => (inode_key(&to), SubtreeStage::DeletingRoot),
+            }
+        } else if let Some(next) = walk.steps.pop() {
+            match next {
+                // Storage lists the direct children of the inode key's path
+                MoveStep::List(dir) => (inode_key(&dir), SubtreeStage::Listing { dir }),
+                MoveStep::Copy(path) => (inode_key(&path), SubtreeStage::ReadingInode { path }),
+            }
+        } else {
+            // Everything is copied: remove the old entries, deepest first,
+            // and the directory itself last
+            match walk.copied.pop() {
+                Some(entry) => (
+                    inode_key(&entry.path),
+                    SubtreeStage::Deleting {
+                        entry,
+                        content: false,
+                    },
+                ),
+                None => {
+                    return self.start_storage_delete(
+                        &inode_key(&from),
+                        PendingOp::RenameOp {
+                            ctx: client_ctx,
+                            from,
+                            to,
+                            perm_ctx,
+                            stage: RenameStage::DeletingSourceInode { is_file: false },
+                            reply,
+                        },
+                    )
+                }
+            }
+        };
+
+        let (list, read) = match step {
+            SubtreeStage::Listing { .. } => (true, false),
+            SubtreeStage::ReadingInode { .. } => (false, true),
+            _ => (false, false),
+        };
+        let op = PendingOp::RenameOp {
+            ctx: client_ctx,
+            from,
+            to,
+            perm_ctx,
+            stage: RenameStage::MovingSubtree { walk, step },
+            reply,
+        };
+        if list {
+            self.start_storage_list(&key, op)
+        } else if read {
+            self.start_storage_read(&key, op)
+        } else {
+            self.start_storage_delete(&key, op)
+        }
+    }
+
+    /// Write a descendant's inode under its new path.
+    #[allow(clippy::too_many_arguments)]
+    fn subtree_write_inode(
+        &mut self,
+        client_ctx: ClientContext,
+        from: String,
+        to: String,
+        perm_ctx: PermissionContext,
+        reply: RenameReply,
+        mut walk: MoveWalk,
+        path: String,
+        mut inode: Box<Inode>,
+    ) -> Result<(), AppError> {
+        let new_path = moved_path(&from, &to, &path);
+        let is_file = inode.is_file();
+        inode.parent_path = parent_path(&new_path);
+        inode.path = new_path;
+
+        match serde_json::to_vec(&inode) {
+            Ok(inode_json) => {
+                let step = SubtreeStage::WritingInode { path, is_file };
+                self.start_storage_write(
+                    &inode_key(&inode.path),
+                    &inode_json,
+                    PendingOp::RenameOp {
+                        ctx: client_ctx,
+                        from,
+                        to,
+                        perm_ctx,
+                        stage: RenameStage::MovingSubtree { walk, step },
+                        reply,
+                    },
+                )
+            }
+            Err(e) => {
+                walk.fail(
+                    &path,
+                    VfsError::StorageError(format!("Failed to serialize inode: {}", e)),
+                );
+                self.subtree_next_step(client_ctx, from, to, perm_ctx, reply, walk)
+            }
+        }
+    }
+
+    fn finish_rename(
+        &self,
+        client_ctx: &ClientContext,
+        from: &str,
+        to: &str,
+        reply: &RenameReply,
+    ) -> Result<(), AppError> {
+        syscall::debug(&format!(
+            "VfsService: rename {} -> {} completed successfully",
+            from, to
+        ));
+        if from != to {
+            self.notify_watchers(VfsEventKind::Rename, from, Some(to));
+        }
+        let tag = reply.response_tag();
+        match reply {
+            RenameReply::Rename => {
+                self.send_response(client_ctx, tag, &RenameResponse { result: Ok(()) })
+            }
+            RenameReply::Trash { .. } => {
+                self.send_response(client_ctx, tag, &UnlinkResponse { result: Ok(()) })
+            }
+            RenameReply::Restore => {
+                let response = TrashRestoreResponse {
+                    result: Ok(to.to_string()),
+                };
+                self.send_response(client_ctx, tag, &response)
+            }
+        }
+    }
+}
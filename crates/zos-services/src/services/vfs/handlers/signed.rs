@@ -0,0 +1,147 @@
+//! Signed request handlers
+//!
+//! Handles: register service key, signed privileged requests
+//!
+//! Privileged operations ordered by another service (e.g. PermissionService
+//! deleting an app's data) arrive wrapped in a `SignedRequest`. The
+//! signature is checked against the key the service pinned at registration,
+//! an audit record is logged, and the inner request is then handled exactly
+//! as if the caller had sent it directly.
+//!
+//! A key is registered under the name the caller runs under (see
+//! `crate::trust`), so a service can pin only its own key, and a restarted
+//! service with a fresh PID can register it again.
+//!
+//! Pinned keys and replay state are checkpointed with the rest of
+//! `VfsState`, so a restart does not reopen registration or the nonce window.
+
+use alloc::format;
+use alloc::string::String;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, Message};
+use zos_vfs::ipc::{vfs_msg, RegisterServiceKeyRequest, RegisterServiceKeyResponse};
+use zos_vfs::VfsError;
+
+use super::super::VfsService;
+use super::checkpoint::InterruptedResponse;
+use crate::services::identity::utils::hex_to_bytes;
+use crate::signing::SignedRequest;
+
+type Handler = fn(&mut VfsService, &AppContext, &Message) -> Result<(), AppError>;
+
+/// Operations that may be requested through a signed envelope, with the
+/// request tag and handler each one is dispatched to.
+const SIGNED_OPERATIONS: &[(&str, u32, Handler)] = &[
+    ("rmdir", vfs_msg::MSG_VFS_RMDIR, VfsService::handle_rmdir),
+    ("unlink", vfs_msg::MSG_VFS_UNLINK, VfsService::handle_unlink),
+];
+
+impl VfsService {
+    /// Handle MSG_VFS_REGISTER_SERVICE_KEY - pin a service's signing key
+    pub fn handle_register_service_key(&mut self, msg: &Message) -> Result<(), AppError> {
+        let ctx = self.client_context(msg);
+        let result = self.register_service_key(msg);
+        if let Err(ref e) = result {
+            syscall::debug(&format!(
+                "VfsService: Service key registration from PID {} refused: {:?}",
+                msg.from_pid, e
+            ));
+        }
+        let response = RegisterServiceKeyResponse { result };
+        self.send_response(
+            &ctx,
+            vfs_msg::MSG_VFS_REGISTER_SERVICE_KEY_RESPONSE,
+            &response,
+        )
+    }
+
+    fn register_service_key(&mut self, msg: &Message) -> Result<(), VfsError> {
+        let request: RegisterServiceKeyRequest = serde_json::from_slice(&msg.data)
+            .map_err(|e| VfsError::InvalidRequest(format!("Failed to parse request: {}", e)))?;
+        // A service registers its own key only, whatever its PID
+        if !self.names.is(msg.from_pid, &request.service) {
+            return Err(VfsError::PermissionDenied);
+        }
+        let public_key: [u8; 32] = hex_to_bytes(&request.public_key)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| VfsError::InvalidRequest(String::from("Invalid public key")))?;
+
+        self.service_keys
+            .register(&request.service, public_key)
+            .map_err(|e| VfsError::InvalidRequest(e.message()))?;
+        syscall::debug(&format!(
+            "VfsService: Service key for {} registered by PID {}",
+            request.service, msg.from_pid
+        ));
+        Ok(())
+    }
+
+    /// Handle MSG_VFS_SIGNED_REQUEST - verify, audit, then dispatch
+    pub fn handle_signed_request(
+        &mut self,
+        ctx: &AppContext,
+        msg: &Message,
+    ) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let request: SignedRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(e) => {
+                let response = InterruptedResponse {
+                    result: Err(VfsError::InvalidRequest(format!(
+                        "Failed to parse signed request: {}",
+                        e
+                    ))),
+                };
+                return self.send_response(
+                    &client_ctx,
+                    vfs_msg::MSG_VFS_SIGNED_REQUEST_RESPONSE,
+                    &response,
+                );
+            }
+        };
+
+        let Some(&(_, inner_tag, handler)) = SIGNED_OPERATIONS
+            .iter()
+            .find(|(name, _, _)| *name == request.operation)
+        else {
+            let response = InterruptedResponse {
+                result: Err(VfsError::InvalidRequest(format!(
+                    "Operation {} cannot be signed",
+                    request.operation
+                ))),
+            };
+            return self.send_response(
+                &client_ctx,
+                vfs_msg::MSG_VFS_SIGNED_REQUEST_RESPONSE,
+                &response,
+            );
+        };
+
+        match self.service_keys.verify(&request, syscall::get_wallclock()) {
+            Ok(audit) => syscall::debug(&audit.log_line()),
+            Err(e) => {
+                syscall::debug(&format!(
+                    "VfsService: SECURITY - Rejected signed {} from PID {} (caller {}): {}",
+                    request.operation,
+                    msg.from_pid,
+                    request.caller,
+                    e.message()
+                ));
+                let response = InterruptedResponse {
+                    result: Err(VfsError::PermissionDenied),
+                };
+                // Every VFS response tag is the request tag plus one
+                return self.send_response(&client_ctx, inner_tag + 1, &response);
+            }
+        }
+
+        let inner = Message {
+            tag: inner_tag,
+            from_pid: msg.from_pid,
+            cap_slots: msg.cap_slots.clone(),
+            correlation_id: msg.correlation_id,
+            data: request.payload.into_bytes(),
+        };
+        handler(self, ctx, &inner)
+    }
+}
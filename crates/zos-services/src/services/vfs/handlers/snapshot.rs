@@ -0,0 +1,1186 @@
+//! Snapshot handlers for VFS Service
+//!
+//! Handles: snapshot create, list, restore and delete
+//!
+//! Taking a snapshot walks the tree from its root, copying every inode into
+//! a manifest (see `zos_vfs::snapshot`). Each file's content gets one more
+//! reference, the way a hard link would (see `link`): the first time, the
+//! content record becomes a blob and the file's content key a link to it.
+//! Nothing is copied and the file carries on as before; its next write
+//! gives it a record of its own. The manifest and the user's index are
+//! written together at the end.
+//!
+//! Restoring walks the live tree first and removes what the snapshot
+//! doesn't have, a file's content before its inode, releasing its quota
+//! charge. Then every snapshot entry is put back top-down: a file gets its
+//! inode and a link to the snapshot's blob, with the count raised and the
+//! content it had released. A file still linked to that blob only gets its
+//! inode back. The snapshot is kept.
+//!
+//! While a tree is being snapshotted or restored, requests that would
+//! change anything in it are refused with `VfsError::Retry`, so the
+//! snapshot is of one point in time. Writes through open handles and
+//! batches are not held back. A user runs one snapshot operation at a
+//! time.
+//!
+//! Snapshots count against no quota. As with hard links, a write-at into a
+//! large (chunked) file a snapshot shares is refused until the file is
+//! rewritten whole or the snapshot deleted.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: every blob a manifest names has a count that includes it
+//! - **Acceptable partial failure**: a create dropped past its deadline
+//!   leaves counts too high, orphaning blobs; a restore that fails part way
+//!   leaves a mix of live and snapshot entries, and can be run again; a
+//!   delete that fails once the index is written orphans the manifest and
+//!   its blobs
+//! - **Forbidden**: snapshotting, restoring or deleting another user's
+//!   snapshots; deleting a blob a manifest still names
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_ipc::storage::ResultKind;
+use zos_vfs::core::is_under;
+use zos_vfs::ipc::{
+    vfs_msg, SnapshotCreateRequest, SnapshotCreateResponse, SnapshotDeleteRequest,
+    SnapshotDeleteResponse, SnapshotInfo, SnapshotListRequest, SnapshotListResponse,
+    SnapshotRestoreRequest, SnapshotRestoreResponse, VfsEventKind,
+};
+use zos_vfs::service::{check_read, check_write, PermissionContext};
+use zos_vfs::snapshot::{
+    decode_index, is_excluded, root_user, snapshot_id, snapshot_index_key, snapshot_key,
+    validate_label, validate_snapshot_id, SnapshotEntry, SnapshotManifest, MAX_SNAPSHOTS_PER_USER,
+};
+use zos_vfs::storage::blobs::{
+    blob_key, content_address, decode_link, decode_refcount, encode_link, encode_refcount, is_link,
+    refcount_key, to_hex, BlobHash,
+};
+use zos_vfs::{normalize_path, parent_path, Inode, UserId, VfsError};
+
+use super::super::{
+    content_key, inode_key, parse_inode, validate_path, Charge, ClientContext, PendingOp,
+    Reservation, VfsService,
+};
+use super::checkpoint::InterruptedResponse;
+use super::link::HeldContent;
+use super::mount::changed_paths;
+use super::storage_failure;
+
+/// What a snapshot job is for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotRequest {
+    /// `MSG_VFS_SNAPSHOT_CREATE`
+    Create,
+    /// `MSG_VFS_SNAPSHOT_LIST`
+    List,
+    /// `MSG_VFS_SNAPSHOT_RESTORE`
+    Restore { id: String },
+    /// `MSG_VFS_SNAPSHOT_DELETE`
+    Delete { id: String },
+}
+
+impl SnapshotRequest {
+    /// Tag the request is answered with.
+    pub fn response_tag(&self) -> u32 {
+        match self {
+            SnapshotRequest::Create => vfs_msg::MSG_VFS_SNAPSHOT_CREATE_RESPONSE,
+            SnapshotRequest::List => vfs_msg::MSG_VFS_SNAPSHOT_LIST_RESPONSE,
+            SnapshotRequest::Restore { .. } => vfs_msg::MSG_VFS_SNAPSHOT_RESTORE_RESPONSE,
+            SnapshotRequest::Delete { .. } => vfs_msg::MSG_VFS_SNAPSHOT_DELETE_RESPONSE,
+        }
+    }
+}
+
+/// One step of a walk over a snapshot's tree.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnapshotStep {
+    /// Read an entry's inode (create: copy it; restore: keep or remove it)
+    Visit(String),
+    /// List a directory and visit its children
+    List(String),
+    /// Remove an entry whose children are gone (restore only)
+    Remove {
+        path: String,
+        is_file: bool,
+        charge: Option<Charge>,
+    },
+}
+
+/// Progress of a snapshot operation.
+#[derive(Clone, Debug)]
+pub struct SnapshotJob {
+    /// User the snapshots belong to
+    pub user_id: UserId,
+    pub perm_ctx: PermissionContext,
+    pub request: SnapshotRequest,
+    /// The user's snapshot index, once read
+    pub index: Vec<SnapshotInfo>,
+    /// The snapshot being taken, restored or deleted, once known
+    pub manifest: Option<SnapshotManifest>,
+    /// Walk steps still to run, taken from the end
+    pub steps: Vec<SnapshotStep>,
+    /// Blob references a create has taken, dropped again if it fails
+    pub held: Vec<BlobHash>,
+    /// Manifest entry a restore is putting back
+    pub next: usize,
+}
+
+impl SnapshotJob {
+    pub fn new(user_id: UserId, perm_ctx: PermissionContext, request: SnapshotRequest) -> Self {
+        Self {
+            user_id,
+            perm_ctx,
+            request,
+            index: Vec::new(),
+            manifest: None,
+            steps: Vec::new(),
+            held: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Tree held still while this job runs.
+    pub fn frozen_root(&self) -> Option<&str> {
+        match self.request {
+            SnapshotRequest::Create | SnapshotRequest::Restore { .. } => {
+                self.manifest.as_ref().map(|m| m.info.root.as_str())
+            }
+            SnapshotRequest::List | SnapshotRequest::Delete { .. } => None,
+        }
+    }
+
+    fn manifest(&mut self) -> Result<&mut SnapshotManifest, VfsError> {
+        self.manifest
+            .as_mut()
+            .ok_or_else(|| VfsError::StorageError("Snapshot manifest not loaded".into()))
+    }
+}
+
+/// Stages for a snapshot job.
+#[derive(Clone, Debug)]
+pub enum SnapshotStage {
+    /// Reading the user's snapshot index
+    ReadingIndex,
+    /// Reading the manifest of the snapshot to restore or delete
+    ReadingManifest,
+    /// Reading an entry's inode
+    ReadingEntry { path: String },
+    /// Listing a directory
+    Listing { dir: String },
+    /// Create: reading a file's content record
+    ReadingContent { entry: usize },
+    /// Create: reading the count of the blob the content is or names
+    ReadingRefcount {
+        entry: usize,
+        hash: BlobHash,
+        /// The file's own content record, which becomes the blob (`None`
+        /// if the file is already a link)
+        record: Option<Vec<u8>>,
+    },
+    /// Create: writing the raised count, with the blob and link record the
+    /// first time the content is shared
+    WritingRefs { entry: usize, hash: BlobHash },
+    /// Restore of a missing root: checking its parent is a writable directory
+    CheckingParent,
+    /// Restore: deleting a removed file's content (its inode is kept if
+    /// this fails)
+    DeletingContent {
+        path: String,
+        charge: Option<Charge>,
+    },
+    /// Restore: deleting a removed entry's inode
+    DeletingInode {
+        path: String,
+        charge: Option<Charge>,
+    },
+    /// Restore: reading what is at the path of the entry being put back
+    ReadingLive,
+    /// Restore: reading the content record a file entry replaces
+    ReadingLiveContent { released: Option<Charge> },
+    /// Restore: reading the count of the blob a file entry names
+    ReadingBlobCount {
+        hash: BlobHash,
+        released: Option<Charge>,
+        held: Option<HeldContent>,
+    },
+    /// Restore: writing an entry's inode, with the raised count and link
+    /// record for a file that gets its content back
+    WritingEntry {
+        reservation: Option<Reservation>,
+        hash: Option<BlobHash>,
+        /// What the replaced content record held, released once written
+        held: Option<HeldContent>,
+        created: bool,
+    },
+    /// Writing the index (and a new snapshot's manifest)
+    WritingIndex,
+    /// Delete: deleting the manifest
+    DeletingManifest,
+}
+
+impl SnapshotStage {
+    /// Blob whose count this stage is reading or changing.
+    pub fn blob(&self) -> Option<&BlobHash> {
+        match self {
+            SnapshotStage::ReadingRefcount { hash, .. }
+            | SnapshotStage::WritingRefs { hash, .. }
+            | SnapshotStage::ReadingBlobCount { hash, .. }
+            | SnapshotStage::WritingEntry {
+                hash: Some(hash), ..
+            } => Some(hash),
+            _ => None,
+        }
+    }
+}
+
+impl PendingOp {
+    /// Snapshot job this operation is a step of.
+    pub fn snapshot_job(&self) -> Option<&SnapshotJob> {
+        match self {
+            PendingOp::SnapshotOp { job, .. } => Some(job),
+            PendingOp::ContentDeleteOp { then, .. } => then.snapshot_job(),
+            _ => None,
+        }
+    }
+}
+
+/// Storage operation that continues a snapshot job.
+enum Next {
+    Read(String, SnapshotStage),
+    List(String, SnapshotStage),
+    Write(String, Vec<u8>, SnapshotStage),
+    Batch(Vec<(String, Vec<u8>)>, SnapshotStage),
+    Delete(String, SnapshotStage),
+    DeleteContent(String, SnapshotStage),
+    /// Run the next walk step
+    Step,
+    /// Answer the client
+    Done,
+}
+
+fn parse_error(e: serde_json::Error) -> VfsError {
+    VfsError::InvalidRequest(format!("Failed to parse request: {}", e))
+}
+
+fn serialize_error(e: serde_json::Error) -> VfsError {
+    VfsError::StorageError(format!("Failed to serialize: {}", e))
+}
+
+/// Parse an inode read for a snapshot, failing closed.
+fn read_inode(step: &str, result_type: ResultKind, data: &[u8]) -> Result<Option<Inode>, VfsError> {
+    match result_type {
+        ResultKind::ReadOk => parse_inode(data)
+            .map(Some)
+            .map_err(|e| VfsError::StorageError(format!("Failed to parse inode: {}", e))),
+        ResultKind::NotFound => Ok(None),
+        _ => Err(storage_failure(step, result_type)),
+    }
+}
+
+impl VfsService {
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_VFS_SNAPSHOT_CREATE - snapshot a directory tree
+    pub fn handle_snapshot_create(&mut self, msg: &Message) -> Result<(), AppError> {
+        let job = serde_json::from_slice::<SnapshotCreateRequest>(&msg.data)
+            .map_err(parse_error)
+            .and_then(|request| {
+                validate_path(&request.path)
+                    .map_err(|reason| VfsError::InvalidPath(reason.into()))?;
+                let root = normalize_path(&request.path)?;
+                if let Some(label) = &request.label {
+                    validate_label(label)?;
+                }
+                let user_id = root_user(&root)?;
+                if is_excluded(&root) {
+                    return Err(VfsError::InvalidRequest(String::from(
+                        "The trash cannot be snapshotted",
+                    )));
+                }
+
+                let created_at = syscall::get_wallclock();
+                let id = snapshot_id(created_at, self.snapshot_seq);
+                self.snapshot_seq = self.snapshot_seq.wrapping_add(1);
+                let perm_ctx = self.permission_context(msg.from_pid, &root);
+                let mut job = SnapshotJob::new(user_id, perm_ctx, SnapshotRequest::Create);
+                job.manifest = Some(SnapshotManifest::new(id, root, request.label, created_at));
+                Ok(job)
+            });
+        self.start_snapshot_job(msg, vfs_msg::MSG_VFS_SNAPSHOT_CREATE_RESPONSE, job)
+    }
+
+    /// Handle MSG_VFS_SNAPSHOT_LIST - list a user's snapshots
+    pub fn handle_snapshot_list(&mut self, msg: &Message) -> Result<(), AppError> {
+        let job = serde_json::from_slice::<SnapshotListRequest>(&msg.data)
+            .map_err(parse_error)
+            .map(|request| self.new_snapshot_job(msg, request.user_id, SnapshotRequest::List));
+        self.start_snapshot_job(msg, vfs_msg::MSG_VFS_SNAPSHOT_LIST_RESPONSE, job)
+    }
+
+    /// Handle MSG_VFS_SNAPSHOT_RESTORE - roll a tree back to a snapshot
+    pub fn handle_snapshot_restore(&mut self, msg: &Message) -> Result<(), AppError> {
+        let job = serde_json::from_slice::<SnapshotRestoreRequest>(&msg.data)
+            .map_err(parse_error)
+            .and_then(|request| {
+                validate_snapshot_id(&request.id)?;
+                let restore = SnapshotRequest::Restore { id: request.id };
+                Ok(self.new_snapshot_job(msg, request.user_id, restore))
+            });
+        self.start_snapshot_job(msg, vfs_msg::MSG_VFS_SNAPSHOT_RESTORE_RESPONSE, job)
+    }
+
+    /// Handle MSG_VFS_SNAPSHOT_DELETE - delete a snapshot
+    pub fn handle_snapshot_delete(&mut self, msg: &Message) -> Result<(), AppError> {
+        let job = serde_json::from_slice::<SnapshotDeleteRequest>(&msg.data)
+            .map_err(parse_error)
+            .and_then(|request| {
+                validate_snapshot_id(&request.id)?;
+                let delete = SnapshotRequest::Delete { id: request.id };
+                Ok(self.new_snapshot_job(msg, request.user_id, delete))
+            });
+        self.start_snapshot_job(msg, vfs_msg::MSG_VFS_SNAPSHOT_DELETE_RESPONSE, job)
+    }
+
+    /// A job on `user_id`'s snapshots, checked as a request for their home.
+    fn new_snapshot_job(
+        &self,
+        msg: &Message,
+        user_id: UserId,
+        request: SnapshotRequest,
+    ) -> SnapshotJob {
+        let home = format!("/home/{}", user_id);
+        let perm_ctx = self.permission_context(msg.from_pid, &home);
+        SnapshotJob::new(user_id, perm_ctx, request)
+    }
+
+    /// Check who is asking, then read the user's index.
+    fn start_snapshot_job(
+        &mut self,
+        msg: &Message,
+        tag: u32,
+        job: Result<SnapshotJob, VfsError>,
+    ) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let checked = job.and_then(|job| {
+            // Only the user, or a system process acting for them
+            if job.perm_ctx.user_id != Some(job.user_id) {
+                syscall::debug(&format!(
+                    "VfsService: Permission denied for {:?} of user {} (pid={})",
+                    job.request, job.user_id, msg.from_pid
+                ));
+                return Err(VfsError::PermissionDenied);
+            }
+            if job.request != SnapshotRequest::List && self.snapshot_running(job.user_id) {
+                return Err(VfsError::retry("Another snapshot operation is running"));
+            }
+            Ok(job)
+        });
+        let job = match checked {
+            Ok(job) => job,
+            Err(error) => return self.send_snapshot_error(&client_ctx, tag, error),
+        };
+
+        syscall::debug(&format!(
+            "VfsService: snapshot {:?} for user {}",
+            job.request, job.user_id
+        ));
+        self.start_storage_read(
+            &snapshot_index_key(job.user_id),
+            PendingOp::SnapshotOp {
+                ctx: client_ctx,
+                job: Box::new(job),
+                stage: SnapshotStage::ReadingIndex,
+            },
+        )
+    }
+
+    /// Whether a create, restore or delete of `user_id`'s snapshots is
+    /// running.
+    fn snapshot_running(&self, user_id: UserId) -> bool {
+        self.pending_ops.values().any(|op| {
+            op.snapshot_job()
+                .is_some_and(|job| job.user_id == user_id && job.request != SnapshotRequest::List)
+        })
+    }
+
+    /// Refuse a request that would change a tree being snapshotted or
+    /// restored.
+    pub fn hold_for_snapshot(&self, msg: &Message) -> Option<Result<(), AppError>> {
+        let roots: Vec<&str> = self
+            .pending_ops
+            .values()
+            .filter_map(|op| op.snapshot_job()?.frozen_root())
+            .collect();
+        if roots.is_empty() {
+            return None;
+        }
+        let held = changed_paths(msg.tag, &msg.data).iter().any(|path| {
+            roots
+                .iter()
+                .any(|root| is_under(path, root) || is_under(root, path))
+        });
+        if !held {
+            return None;
+        }
+        syscall::debug(&format!(
+            "VfsService: Holding back tag 0x{:x} from PID {} during a snapshot",
+            msg.tag, msg.from_pid
+        ));
+        let response = InterruptedResponse {
+            result: Err(VfsError::retry("Snapshot in progress")),
+        };
+        // Every VFS response tag is the request tag plus one
+        Some(self.send_response(&self.client_context(msg), msg.tag + 1, &response))
+    }
+
+    /// Send the error response for a snapshot request.
+    fn send_snapshot_error(
+        &self,
+        client_ctx: &ClientContext,
+        tag: u32,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        match tag {
+            vfs_msg::MSG_VFS_SNAPSHOT_CREATE_RESPONSE => {
+                let response = SnapshotCreateResponse { result: Err(error) };
+                self.send_response(client_ctx, tag, &response)
+            }
+            vfs_msg::MSG_VFS_SNAPSHOT_LIST_RESPONSE => {
+                let response = SnapshotListResponse { result: Err(error) };
+                self.send_response(client_ctx, tag, &response)
+            }
+            vfs_msg::MSG_VFS_SNAPSHOT_RESTORE_RESPONSE => {
+                let response = SnapshotRestoreResponse { result: Err(error) };
+                self.send_response(client_ctx, tag, &response)
+            }
+            _ => {
+                let response = SnapshotDeleteResponse { result: Err(error) };
+                self.send_response(client_ctx, tag, &response)
+            }
+        }
+    }
+
+    // =========================================================================
+    // State machine
+    // =========================================================================
+
+    /// Handle snapshot operation result - dispatches based on stage
+    pub fn handle_snapshot_op_result(
+        &mut self,
+        client_ctx: ClientContext,
+        mut job: Box<SnapshotJob>,
+        stage: SnapshotStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let next = match stage {
+            SnapshotStage::ReadingIndex => Self::snapshot_read_index(&mut job, result_type, data),
+            SnapshotStage::ReadingManifest => {
+                Self::snapshot_read_manifest(&mut job, result_type, data)
+            }
+            SnapshotStage::ReadingEntry { path } => {
+                let create = job.request == SnapshotRequest::Create;
+                read_inode("Inode read", result_type, data).and_then(|inode| {
+                    if create {
+                        Self::snapshot_visit(&mut job, path, inode)
+                    } else {
+                        Self::restore_visit(&mut job, path, inode)
+                    }
+                })
+            }
+            SnapshotStage::Listing { dir } => match result_type {
+                ResultKind::ListOk => serde_json::from_slice::<Vec<String>>(data)
+                    .map_err(|e| {
+                        VfsError::StorageError(format!("Failed to parse child list: {}", e))
+                    })
+                    .map(|children| {
+                        let children = children.into_iter().filter(|child| !is_excluded(child));
+                        job.steps.extend(children.map(SnapshotStep::Visit));
+                        Next::Step
+                    }),
+                ResultKind::NotFound => Ok(Next::Step),
+                _ => Err(storage_failure(&format!("List of {}", dir), result_type)),
+            },
+            SnapshotStage::ReadingContent { entry } => {
+                self.snapshot_read_content(entry, result_type, data)
+            }
+            SnapshotStage::ReadingRefcount {
+                entry,
+                hash,
+                record,
+            } => Self::snapshot_read_refcount(&mut job, entry, hash, record, result_type, data),
+            SnapshotStage::WritingRefs { entry, hash } => match result_type {
+                ResultKind::WriteOk => {
+                    job.held.push(hash);
+                    job.manifest().map(|manifest| {
+                        manifest.entries[entry].content = Some(hash);
+                        Next::Step
+                    })
+                }
+                _ => Err(storage_failure("Snapshot reference write", result_type)),
+            },
+            SnapshotStage::CheckingParent => match read_inode("Parent read", result_type, data) {
+                Ok(Some(parent)) if !parent.is_directory() => Err(VfsError::NotADirectory),
+                Ok(Some(parent)) if !check_write(&parent, &job.perm_ctx) => {
+                    Err(VfsError::PermissionDenied)
+                }
+                // Nothing left to remove: start putting entries back
+                Ok(Some(_)) => Ok(Next::Step),
+                Ok(None) => Err(VfsError::NotFound),
+                Err(error) => Err(error),
+            },
+            SnapshotStage::DeletingContent { path, charge } => match result_type {
+                ResultKind::WriteOk | ResultKind::NotFound => Ok(Next::Delete(
+                    inode_key(&path),
+                    SnapshotStage::DeletingInode { path, charge },
+                )),
+                _ => Err(storage_failure("Content delete", result_type)),
+            },
+            SnapshotStage::DeletingInode { path, charge } => match result_type {
+                ResultKind::WriteOk => {
+                    self.quotas.release(charge);
+                    self.notify_watchers(VfsEventKind::Delete, &path, None);
+                    Ok(Next::Step)
+                }
+                ResultKind::NotFound => Ok(Next::Step),
+                _ => Err(storage_failure("Inode delete", result_type)),
+            },
+            SnapshotStage::ReadingLive => self.restore_read_live(&mut job, result_type, data),
+            SnapshotStage::ReadingLiveContent { released } => {
+                self.restore_read_live_content(&mut job, released, result_type, data)
+            }
+            SnapshotStage::ReadingBlobCount {
+                hash,
+                released,
+                held,
+            } => self.restore_read_count(&mut job, hash, released, held, result_type, data),
+            SnapshotStage::WritingEntry {
+                reservation,
+                held,
+                created,
+                ..
+            } => {
+                if result_type == ResultKind::WriteOk {
+                    if let Some(held) = held {
+                        self.release_held(held);
+                    }
+                    let entry = job
+                        .manifest
+                        .as_ref()
+                        .and_then(|manifest| manifest.entries.get(job.next));
+                    if let Some(entry) = entry {
+                        if entry.inode.is_file() {
+                            self.notify_watchers(VfsEventKind::Write, &entry.inode.path, None);
+                        } else if created {
+                            self.notify_watchers(VfsEventKind::Create, &entry.inode.path, None);
+                        }
+                    }
+                    job.next += 1;
+                    Ok(Next::Step)
+                } else {
+                    if let Some(reservation) = reservation {
+                        self.quotas.undo(&reservation);
+                    }
+                    Err(storage_failure("Restore write", result_type))
+                }
+            }
+            SnapshotStage::WritingIndex => match (result_type, &job.request) {
+                (ResultKind::WriteOk, SnapshotRequest::Delete { id }) => Ok(Next::Delete(
+                    snapshot_key(job.user_id, id),
+                    SnapshotStage::DeletingManifest,
+                )),
+                (ResultKind::WriteOk, _) => {
+                    // The manifest holds the references now
+                    job.held.clear();
+                    Ok(Next::Done)
+                }
+                _ => Err(storage_failure("Snapshot index write", result_type)),
+            },
+            SnapshotStage::DeletingManifest => match result_type {
+                ResultKind::WriteOk | ResultKind::NotFound => {
+                    let blobs: Vec<BlobHash> = job
+                        .manifest
+                        .as_ref()
+                        .map(|manifest| manifest.blobs().copied().collect())
+                        .unwrap_or_default();
+                    for hash in blobs {
+                        self.release_held(HeldContent::Blob(hash));
+                    }
+                    Ok(Next::Done)
+                }
+                _ => Err(storage_failure("Snapshot delete", result_type)),
+            },
+        };
+
+        let next = next.and_then(|next| match next {
+            Next::Step => self.snapshot_step(&mut job),
+            next => Ok(next),
+        });
+        match next {
+            Ok(next) => self.continue_snapshot(client_ctx, job, next),
+            Err(error) => self.fail_snapshot(&client_ctx, job, error),
+        }
+    }
+
+    /// Start the storage operation `next` names.
+    fn continue_snapshot(
+        &mut self,
+        client_ctx: ClientContext,
+        job: Box<SnapshotJob>,
+        next: Next,
+    ) -> Result<(), AppError> {
+        if let Next::Step | Next::Done = next {
+            return self.finish_snapshot(&client_ctx, *job);
+        }
+        let op = move |stage| PendingOp::SnapshotOp {
+            ctx: client_ctx,
+            job,
+            stage,
+        };
+        match next {
+            Next::Read(key, stage) => self.start_storage_read(&key, op(stage)),
+            Next::List(key, stage) => self.start_storage_list(&key, op(stage)),
+            Next::Write(key, value, stage) => {
+                let reservation = match &stage {
+                    SnapshotStage::WritingEntry { reservation, .. } => *reservation,
+                    _ => None,
+                };
+                let started = self.start_storage_write(&key, &value, op(stage));
+                if started.is_err() {
+                    if let Some(reservation) = reservation {
+                        self.quotas.undo(&reservation);
+                    }
+                }
+                started
+            }
+            Next::Batch(items, stage) => {
+                let reservation = match &stage {
+                    SnapshotStage::WritingEntry { reservation, .. } => *reservation,
+                    _ => None,
+                };
+                let items: Vec<(&str, &[u8])> = items
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.as_slice()))
+                    .collect();
+                let started = self.start_storage_batch_write(&items, op(stage));
+                if started.is_err() {
+                    if let Some(reservation) = reservation {
+                        self.quotas.undo(&reservation);
+                    }
+                }
+                started
+            }
+            Next::Delete(key, stage) => self.start_storage_delete(&key, op(stage)),
+            Next::DeleteContent(path, stage) => self.start_content_delete(&path, op(stage)),
+            Next::Step | Next::Done => Ok(()),
+        }
+    }
+
+    /// Index read - find the snapshot, or start the walk of a new one.
+    fn snapshot_read_index(
+        job: &mut SnapshotJob,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<Next, VfsError> {
+        job.index = match result_type {
+            ResultKind::ReadOk => decode_index(data)?,
+            // No snapshots yet
+            ResultKind::NotFound => Vec::new(),
+            _ => return Err(storage_failure("Snapshot index read", result_type)),
+        };
+        match &job.request {
+            SnapshotRequest::List => Ok(Next::Done),
+            SnapshotRequest::Create if job.index.len() >= MAX_SNAPSHOTS_PER_USER => {
+                Err(VfsError::QuotaExceeded)
+            }
+            SnapshotRequest::Create => {
+                let root = job.manifest()?.info.root.clone();
+                job.steps.push(SnapshotStep::Visit(root));
+                Ok(Next::Step)
+            }
+            SnapshotRequest::Restore { id } | SnapshotRequest::Delete { id } => {
+                if !job.index.iter().any(|info| info.id == *id) {
+                    return Err(VfsError::NotFound);
+                }
+                Ok(Next::Read(
+                    snapshot_key(job.user_id, id),
+                    SnapshotStage::ReadingManifest,
+                ))
+            }
+        }
+    }
+
+    /// Manifest read - start a restore's walk, or drop the snapshot from
+    /// the index.
+    fn snapshot_read_manifest(
+        job: &mut SnapshotJob,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<Next, VfsError> {
+        let manifest = match result_type {
+            ResultKind::ReadOk => SnapshotManifest::decode(data),
+            ResultKind::NotFound => Err(VfsError::StorageError("Snapshot manifest missing".into())),
+            _ => Err(storage_failure("Snapshot read", result_type)),
+        };
+        match &job.request {
+            SnapshotRequest::Delete { id } => {
+                // A damaged snapshot can still be deleted; its blobs are
+                // orphaned
+                job.manifest = manifest.ok();
+                job.index.retain(|info| info.id != *id);
+                let index = serde_json::to_vec(&job.index).map_err(serialize_error)?;
+                Ok(Next::Write(
+                    snapshot_index_key(job.user_id),
+                    index,
+                    SnapshotStage::WritingIndex,
+                ))
+            }
+            _ => {
+                let manifest = manifest?;
+                job.steps
+                    .push(SnapshotStep::Visit(manifest.info.root.clone()));
+                job.manifest = Some(manifest);
+                Ok(Next::Step)
+            }
+        }
+    }
+
+    /// Run the next walk step; once the walk is done, commit a new
+    /// snapshot or start putting a restored one back.
+    fn snapshot_step(&mut self, job: &mut SnapshotJob) -> Result<Next, VfsError> {
+        match job.steps.pop() {
+            Some(SnapshotStep::Visit(path)) => Ok(Next::Read(
+                inode_key(&path),
+                SnapshotStage::ReadingEntry { path },
+            )),
+            Some(SnapshotStep::List(dir)) => {
+                Ok(Next::List(inode_key(&dir), SnapshotStage::Listing { dir }))
+            }
+            Some(SnapshotStep::Remove {
+                path,
+                is_file: true,
+                charge,
+            }) => Ok(Next::DeleteContent(
+                path.clone(),
+                SnapshotStage::DeletingContent { path, charge },
+            )),
+            Some(SnapshotStep::Remove { path, charge, .. }) => Ok(Next::Delete(
+                inode_key(&path),
+                SnapshotStage::DeletingInode { path, charge },
+            )),
+            None if job.request == SnapshotRequest::Create => {
+                let user_id = job.user_id;
+                let manifest = job.manifest()?;
+                manifest.finish();
+                let info = manifest.info.clone();
+                let record = manifest.encode()?;
+                job.index.push(info.clone());
+                let index = serde_json::to_vec(&job.index).map_err(serialize_error)?;
+                Ok(Next::Batch(
+                    Vec::from([
+                        (snapshot_key(user_id, &info.id), record),
+                        (snapshot_index_key(user_id), index),
+                    ]),
+                    SnapshotStage::WritingIndex,
+                ))
+            }
+            None => {
+                let next = job.next;
+                match job.manifest()?.entries.get(next) {
+                    Some(entry) => Ok(Next::Read(
+                        inode_key(&entry.inode.path),
+                        SnapshotStage::ReadingLive,
+                    )),
+                    None => Ok(Next::Done),
+                }
+            }
+        }
+    }
+
+    // =========================================================================
+    // Create
+    // =========================================================================
+
+    /// Copy an entry into the new snapshot.
+    fn snapshot_visit(
+        job: &mut SnapshotJob,
+        path: String,
+        inode: Option<Inode>,
+    ) -> Result<Next, VfsError> {
+        let user_id = job.user_id;
+        let allowed = inode.as_ref().map(|inode| {
+            check_read(inode, &job.perm_ctx) && inode.owner_id.is_none_or(|owner| owner == user_id)
+        });
+        let manifest = job.manifest()?;
+        let is_root = path == manifest.info.root;
+        let inode = match (inode, allowed) {
+            (Some(_), Some(false)) => {
+                // Blobs are only shared between one user's files
+                syscall::debug(&format!(
+                    "VfsService: Permission denied for snapshot of {}",
+                    path
+                ));
+                return Err(VfsError::PermissionDenied);
+            }
+            (Some(inode), _) if is_root && !inode.is_directory() => {
+                return Err(VfsError::NotADirectory)
+            }
+            (Some(inode), _) => inode,
+            (None, _) if is_root => return Err(VfsError::NotFound),
+            // Gone since its directory was listed
+            (None, _) => return Ok(Next::Step),
+        };
+
+        let is_directory = inode.is_directory();
+        let is_file = inode.is_file();
+        manifest.push(SnapshotEntry {
+            inode,
+            content: None,
+        })?;
+        let entry = manifest.entries.len() - 1;
+        if is_directory {
+            job.steps.push(SnapshotStep::List(path));
+            Ok(Next::Step)
+        } else if is_file {
+            Ok(Next::Read(
+                content_key(&path),
+                SnapshotStage::ReadingContent { entry },
+            ))
+        } else {
+            Ok(Next::Step)
+        }
+    }
+
+    /// File content read - find the blob it is or names.
+    fn snapshot_read_content(
+        &self,
+        entry: usize,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<Next, VfsError> {
+        let (hash, record) = match result_type {
+            ResultKind::ReadOk if is_link(data) => (decode_link(data)?, None),
+            ResultKind::ReadOk => (content_address(data), Some(data.to_vec())),
+            ResultKind::NotFound => {
+                return Err(VfsError::StorageError(
+                    "Content missing for existing inode".into(),
+                ))
+            }
+            _ => return Err(storage_failure("Content read", result_type)),
+        };
+        if self.blob_in_use(&hash) {
+            return Err(VfsError::retry("Snapshotted content is being updated"));
+        }
+        Ok(Next::Read(
+            refcount_key(&hash),
+            SnapshotStage::ReadingRefcount {
+                entry,
+                hash,
+                record,
+            },
+        ))
+    }
+
+    /// Blob count read - take the snapshot's reference.
+    ///
+    /// A file not yet shared has its own record become the blob (unless an
+    /// identical one is already stored) and replaced by a link record, so
+    /// the count goes up by two.
+    fn snapshot_read_refcount(
+        job: &mut SnapshotJob,
+        entry: usize,
+        hash: BlobHash,
+        record: Option<Vec<u8>>,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<Next, VfsError> {
+        let count = match result_type {
+            ResultKind::ReadOk => decode_refcount(data)?,
+            // No blob yet: the file's record is about to become it
+            ResultKind::NotFound if record.is_some() => 0,
+            ResultKind::NotFound => {
+                return Err(VfsError::StorageError(format!(
+                    "Blob {} has no refcount",
+                    to_hex(&hash)
+                )))
+            }
+            _ => return Err(storage_failure("Refcount read", result_type)),
+        };
+        let path = job.manifest()?.entries[entry].inode.path.clone();
+
+        let mut items = Vec::with_capacity(3);
+        let added = match record {
+            Some(record) => {
+                if count == 0 {
+                    items.push((blob_key(&hash), record));
+                }
+                items.push((content_key(&path), encode_link(&hash)));
+                2
+            }
+            None => 1,
+        };
+        items.push((refcount_key(&hash), encode_refcount(count + added)));
+        Ok(Next::Batch(
+            items,
+            SnapshotStage::WritingRefs { entry, hash },
+        ))
+    }
+
+    // =========================================================================
+    // Restore
+    // =========================================================================
+
+    /// Keep a live entry the snapshot has, or queue its removal.
+    fn restore_visit(
+        job: &mut SnapshotJob,
+        path: String,
+        live: Option<Inode>,
+    ) -> Result<Next, VfsError> {
+        let perm_ctx = job.perm_ctx.clone();
+        let manifest = job.manifest()?;
+        let is_root = path == manifest.info.root;
+        let Some(live) = live else {
+            if is_root {
+                return Ok(Next::Read(
+                    inode_key(&parent_path(&path)),
+                    SnapshotStage::CheckingParent,
+                ));
+            }
+            return Ok(Next::Step);
+        };
+        if is_root && !check_write(&live, &perm_ctx) {
+            syscall::debug(&format!(
+                "VfsService: Permission denied for snapshot restore of {}",
+                path
+            ));
+            return Err(VfsError::PermissionDenied);
+        }
+
+        let keep = manifest
+            .entry(&path)
+            .is_some_and(|entry| entry.inode.inode_type == live.inode_type);
+        let is_directory = live.is_directory();
+        if !keep {
+            job.steps.push(SnapshotStep::Remove {
+                path: path.clone(),
+                is_file: live.is_file(),
+                charge: Charge::of(&live),
+            });
+        }
+        if is_directory {
+            job.steps.push(SnapshotStep::List(path));
+        }
+        Ok(Next::Step)
+    }
+
+    /// Live inode read - put back a directory, or find what a file replaces.
+    fn restore_read_live(
+        &mut self,
+        job: &mut SnapshotJob,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<Next, VfsError> {
+        let live = read_inode("Inode read", result_type, data)?;
+        let next = job.next;
+        let entry = &job.manifest()?.entries[next];
+        let released = live.as_ref().and_then(Charge::of);
+
+        if !entry.inode.is_file() {
+            let inode_json = serde_json::to_vec(&entry.inode).map_err(serialize_error)?;
+            return Ok(Next::Write(
+                inode_key(&entry.inode.path),
+                inode_json,
+                SnapshotStage::WritingEntry {
+                    reservation: None,
+                    hash: None,
+                    held: None,
+                    created: live.is_none(),
+                },
+            ));
+        }
+        if live.as_ref().is_some_and(Inode::is_file) {
+            return Ok(Next::Read(
+                content_key(&entry.inode.path),
+                SnapshotStage::ReadingLiveContent { released },
+            ));
+        }
+        self.restore_count(job, released, None)
+    }
+
+    /// Content read for a live file - keep it if it is the snapshot's.
+    fn restore_read_live_content(
+        &mut self,
+        job: &mut SnapshotJob,
+        released: Option<Charge>,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<Next, VfsError> {
+        let held = match result_type {
+            ResultKind::ReadOk => HeldContent::of(data)?,
+            ResultKind::NotFound => None,
+            _ => return Err(storage_failure("Content read", result_type)),
+        };
+        let next = job.next;
+        let entry = &job.manifest()?.entries[next];
+        if held.is_some() && held == entry.content.map(HeldContent::Blob) {
+            // Unchanged since the snapshot: only the inode goes back
+            let reservation = self.quotas.reserve(Charge::of(&entry.inode), released)?;
+            let inode_json = match serde_json::to_vec(&entry.inode) {
+                Ok(json) => json,
+                Err(e) => {
+                    self.quotas.undo(&reservation);
+                    return Err(serialize_error(e));
+                }
+            };
+            return Ok(Next::Write(
+                inode_key(&entry.inode.path),
+                inode_json,
+                SnapshotStage::WritingEntry {
+                    reservation: Some(reservation),
+                    hash: None,
+                    held: None,
+                    created: false,
+                },
+            ));
+        }
+        self.restore_count(job, released, held)
+    }
+
+    /// Read the count of the blob a file entry names.
+    fn restore_count(
+        &self,
+        job: &mut SnapshotJob,
+        released: Option<Charge>,
+        held: Option<HeldContent>,
+    ) -> Result<Next, VfsError> {
+        let next = job.next;
+        let entry = &job.manifest()?.entries[next];
+        let hash = entry.content.ok_or_else(|| {
+            VfsError::StorageError(format!("Snapshot has no content for {}", entry.inode.path))
+        })?;
+        if self.blob_in_use(&hash) {
+            return Err(VfsError::retry("Snapshotted content is being updated"));
+        }
+        Ok(Next::Read(
+            refcount_key(&hash),
+            SnapshotStage::ReadingBlobCount {
+                hash,
+                released,
+                held,
+            },
+        ))
+    }
+
+    /// Blob count read - write the file back in one batch.
+    fn restore_read_count(
+        &mut self,
+        job: &mut SnapshotJob,
+        hash: BlobHash,
+        released: Option<Charge>,
+        held: Option<HeldContent>,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<Next, VfsError> {
+        let count = match result_type {
+            ResultKind::ReadOk => decode_refcount(data)?,
+            ResultKind::NotFound => {
+                return Err(VfsError::StorageError(format!(
+                    "Blob {} has no refcount",
+                    to_hex(&hash)
+                )))
+            }
+            _ => return Err(storage_failure("Refcount read", result_type)),
+        };
+        let next = job.next;
+        let entry = &job.manifest()?.entries[next];
+        let inode_json = serde_json::to_vec(&entry.inode).map_err(serialize_error)?;
+        let reservation = self.quotas.reserve(Charge::of(&entry.inode), released)?;
+        let path = &entry.inode.path;
+        Ok(Next::Batch(
+            Vec::from([
+                (refcount_key(&hash), encode_refcount(count + 1)),
+                (content_key(path), encode_link(&hash)),
+                (inode_key(path), inode_json),
+            ]),
+            SnapshotStage::WritingEntry {
+                reservation: Some(reservation),
+                hash: Some(hash),
+                held,
+                created: released.is_none(),
+            },
+        ))
+    }
+
+    // =========================================================================
+    // Replies
+    // =========================================================================
+
+    /// Answer the job's client.
+    fn finish_snapshot(
+        &self,
+        client_ctx: &ClientContext,
+        job: SnapshotJob,
+    ) -> Result<(), AppError> {
+        let tag = job.request.response_tag();
+        match job.request {
+            SnapshotRequest::Create => {
+                let result = job
+                    .manifest
+                    .map(|manifest| manifest.info)
+                    .ok_or_else(|| VfsError::StorageError("Snapshot manifest not loaded".into()));
+                if let Ok(info) = &result {
+                    syscall::debug(&format!(
+                        "VfsService: snapshot {} of {} taken ({} files)",
+                        info.id, info.root, info.files
+                    ));
+                }
+                self.send_response(client_ctx, tag, &SnapshotCreateResponse { result })
+            }
+            SnapshotRequest::List => {
+                let response = SnapshotListResponse {
+                    result: Ok(job.index),
+                };
+                self.send_response(client_ctx, tag, &response)
+            }
+            SnapshotRequest::Restore { id } => {
+                syscall::debug(&format!(
+                    "VfsService: snapshot {} of user {} restored",
+                    id, job.user_id
+                ));
+                self.send_response(client_ctx, tag, &SnapshotRestoreResponse { result: Ok(()) })
+            }
+            SnapshotRequest::Delete { id } => {
+                syscall::debug(&format!(
+                    "VfsService: snapshot {} of user {} deleted",
+                    id, job.user_id
+                ));
+                self.send_response(client_ctx, tag, &SnapshotDeleteResponse { result: Ok(()) })
+            }
+        }
+    }
+
+    /// Answer the job's client with an error, dropping the references a
+    /// create had taken.
+    fn fail_snapshot(
+        &mut self,
+        client_ctx: &ClientContext,
+        job: Box<SnapshotJob>,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        syscall::debug(&format!(
+            "VfsService: snapshot {:?} for user {} failed: {:?}",
+            job.request, job.user_id, error
+        ));
+        for hash in &job.held {
+            self.release_held(HeldContent::Blob(*hash));
+        }
+        self.send_snapshot_error(client_ctx, job.request.response_tag(), error)
+    }
+}
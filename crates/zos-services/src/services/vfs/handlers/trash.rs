@@ -0,0 +1,709 @@
+//! Trash handlers for VFS Service
+//!
+//! Handles: unlink into the trash, trash list, restore and purge, and the
+//! background purge of old entries
+//!
+//! An unlink with `trash` set moves the file or directory to
+//! `/home/{user}/.trash` for the user the caller acts as, through the rename
+//! state machine: the moved inode is marked with where it came from (see
+//! `zos_vfs::trash`) and the trash directory is created with the first
+//! entry. A directory takes its contents with it. Restore runs the same
+//! machine back, to the original path unless the client names another.
+//!
+//! List and purge walk one trash directory, reading each entry's inode in
+//! turn. A purged file has its content deleted before its inode, as in an
+//! unlink, and its owner's quota charge is released. A purged directory is
+//! removed the way a recursive rmdir removes it (see [`RmdirWalk`]). Every
+//! `TRASH_SWEEP_INTERVAL_MS` the service walks every user's trash the same
+//! way and purges entries trashed more than `DEFAULT_TRASH_MAX_AGE_MS` ago.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: a trashed entry is at exactly one path, and a purge
+//!   releases exactly the charges of the files it deleted
+//! - **Acceptable partial failure**: orphan content when an inode delete
+//!   fails after its content; a purge that fails part way leaves the rest of
+//!   the trash in place, directories still holding what couldn't be
+//!   removed, and reports the first failure
+//! - **Forbidden**: Listing a trash without read permission on it, purging
+//!   entries without write permission on them
+
+use alloc::format;
+use core::cmp::Reverse;
+use alloc::string::String;
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_ipc::storage::ResultKind;
+use zos_vfs::core::{extract_user_id, is_under, parent_path};
+use zos_vfs::ipc::{
+    vfs_msg, RmdirFailure, TrashEntry, TrashListRequest, TrashListResponse, TrashPurgeRequest,
+    TrashPurgeResponse, TrashRestoreRequest, TrashRestoreResponse, UnlinkResponse, VfsEventKind,
+};
+use zos_vfs::service::{check_read, check_write, PermissionContext, ProcessClass};
+use zos_vfs::trash::{
+    entry_id, entry_of, is_expired, trash_dir, trash_path, validate_entry_id,
+    DEFAULT_TRASH_MAX_AGE_MS,
+};
+use zos_vfs::{UserId, VfsError};
+
+use super::super::{
+    inode_key, parse_inode, validate_path, Charge, ClientContext, PendingOp, RenameReply,
+    RenameStage, RmdirStep, RmdirWalk, VfsService,
+};
+use super::storage_failure;
+
+/// Uptime at which the first background purge runs, leaving boot alone.
+pub const TRASH_FIRST_SWEEP_MS: u64 = 60 * 1000;
+
+/// Interval between background purges of old trash entries (6 hours).
+pub const TRASH_SWEEP_INTERVAL_MS: u64 = 6 * 60 * 60 * 1000;
+
+/// What a trash walk is for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrashRequest {
+    /// `MSG_VFS_TRASH_LIST`
+    List,
+    /// `MSG_VFS_TRASH_PURGE`: one entry, or all of them
+    Purge { id: Option<String> },
+    /// Background sweep: purge entries trashed too long before `now`
+    Expire { now: u64 },
+}
+
+impl TrashRequest {
+    /// Tag the request is answered with (the sweep answers no one).
+    pub fn response_tag(&self) -> u32 {
+        match self {
+            TrashRequest::List => vfs_msg::MSG_VFS_TRASH_LIST_RESPONSE,
+            TrashRequest::Purge { .. } | TrashRequest::Expire { .. } => {
+                vfs_msg::MSG_VFS_TRASH_PURGE_RESPONSE
+            }
+        }
+    }
+}
+
+/// Progress of a trash walk.
+#[derive(Clone, Debug, Default)]
+pub struct TrashScan {
+    /// Entry paths still to read, taken from the end
+    pub queue: Vec<String>,
+    /// Entries found (list only)
+    pub entries: Vec<TrashEntry>,
+    /// Entries deleted so far
+    pub purged: u32,
+    /// Removal of a purged directory, run before the next entry is read
+    pub tree: RmdirWalk,
+    /// First failure, reported once the walk is done
+    pub error: Option<VfsError>,
+}
+
+impl TrashScan {
+    /// Record a failure; the walk carries on with the next entry.
+    fn fail(&mut self, path: &str, error: VfsError) {
+        syscall::debug(&format!("VfsService: trash {}: {:?}", path, error));
+        // Keeps the directories above it
+        self.tree.failed.push(RmdirFailure {
+            path: path.into(),
+            error: error.clone(),
+        });
+        self.error.get_or_insert(error);
+    }
+}
+
+/// Stages for a trash walk.
+#[derive(Clone, Debug)]
+pub enum TrashStage {
+    /// Reading the trash directory to check permissions
+    ReadingDir,
+    /// Listing the trash directory
+    Listing,
+    /// Reading an entry's inode
+    ReadingEntry { path: String },
+    /// Listing a directory being purged
+    ListingTree { dir: String },
+    /// Reading the inode of something inside a directory being purged
+    ReadingTreeEntry { path: String },
+    /// Deleting a purged file's content (its inode is kept if this fails)
+    DeletingContent {
+        path: String,
+        charge: Option<Charge>,
+    },
+    /// Deleting a purged file or directory's inode
+    DeletingInode {
+        path: String,
+        charge: Option<Charge>,
+    },
+}
+
+/// Trash entry naming and the background purge of old entries.
+#[derive(Default)]
+pub struct TrashSweep {
+    /// Tells apart files with one name trashed in the same millisecond
+    next_seq: u32,
+    /// Uptime (ms) the next sweep is due at, set on the first update
+    next_sweep_ms: Option<u64>,
+    /// Users whose trash the running sweep is yet to walk
+    users: Vec<UserId>,
+    /// Whether a sweep is running
+    running: bool,
+}
+
+impl VfsService {
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Move an entry being unlinked to the trash of the user the caller acts as.
+    pub fn start_trash_move(&mut self, msg: &Message, path: String) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let perm_ctx = self.permission_context(msg.from_pid, &path);
+        let Some(user_id) = perm_ctx.user_id else {
+            let response = UnlinkResponse {
+                result: Err(VfsError::InvalidRequest(String::from(
+                    "No user whose trash to move the entry to",
+                ))),
+            };
+            return self.send_response(&client_ctx, vfs_msg::MSG_VFS_UNLINK_RESPONSE, &response);
+        };
+
+        let deleted_at = syscall::get_wallclock();
+        let seq = self.trash.next_seq;
+        self.trash.next_seq = seq.wrapping_add(1);
+        let name = path.rsplit('/').next().unwrap_or(&path);
+        let to = trash_path(user_id, &entry_id(deleted_at, seq, name));
+
+        syscall::debug(&format!("VfsService: trash {} -> {}", path, to));
+
+        self.start_storage_read(
+            &inode_key(&path),
+            PendingOp::RenameOp {
+                ctx: client_ctx,
+                from: path,
+                to,
+                perm_ctx,
+                stage: RenameStage::ReadingSource,
+                reply: RenameReply::Trash { deleted_at },
+            },
+        )
+    }
+
+    /// Handle MSG_VFS_TRASH_LIST - list a user's trashed entries
+    pub fn handle_trash_list(&mut self, msg: &Message) -> Result<(), AppError> {
+        match serde_json::from_slice::<TrashListRequest>(&msg.data) {
+            Ok(request) => self.start_trash_walk(msg, request.user_id, TrashRequest::List),
+            Err(e) => {
+                let response = TrashListResponse {
+                    result: Err(VfsError::InvalidRequest(format!(
+                        "Failed to parse request: {}",
+                        e
+                    ))),
+                };
+                self.send_response(
+                    &self.client_context(msg),
+                    vfs_msg::MSG_VFS_TRASH_LIST_RESPONSE,
+                    &response,
+                )
+            }
+        }
+    }
+
+    /// Handle MSG_VFS_TRASH_PURGE - delete trashed entries for good
+    pub fn handle_trash_purge(&mut self, msg: &Message) -> Result<(), AppError> {
+        let request = serde_json::from_slice::<TrashPurgeRequest>(&msg.data)
+            .map_err(|e| VfsError::InvalidRequest(format!("Failed to parse request: {}", e)))
+            .and_then(|request| match &request.id {
+                Some(id) => validate_entry_id(id).map(|()| request),
+                None => Ok(request),
+            });
+        match request {
+            Ok(request) => {
+                self.start_trash_walk(msg, request.user_id, TrashRequest::Purge { id: request.id })
+            }
+            Err(error) => {
+                let response = TrashPurgeResponse { result: Err(error) };
+                self.send_response(
+                    &self.client_context(msg),
+                    vfs_msg::MSG_VFS_TRASH_PURGE_RESPONSE,
+                    &response,
+                )
+            }
+        }
+    }
+
+    /// Handle MSG_VFS_TRASH_RESTORE - move a trashed entry back
+    pub fn handle_trash_restore(&mut self, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let request = serde_json::from_slice::<TrashRestoreRequest>(&msg.data)
+            .map_err(|e| VfsError::InvalidRequest(format!("Failed to parse request: {}", e)))
+            .and_then(|request| {
+                validate_entry_id(&request.id)?;
+                if let Some(to) = &request.to {
+                    validate_path(to).map_err(|reason| VfsError::InvalidPath(reason.into()))?;
+                }
+                Ok(request)
+            });
+        let request = match request {
+            Ok(request) => request,
+            Err(error) => {
+                let response = TrashRestoreResponse { result: Err(error) };
+                return self.send_response(
+                    &client_ctx,
+                    vfs_msg::MSG_VFS_TRASH_RESTORE_RESPONSE,
+                    &response,
+                );
+            }
+        };
+
+        let from = trash_path(request.user_id, &request.id);
+        syscall::debug(&format!("VfsService: restore {} from trash", from));
+
+        let perm_ctx = self.permission_context(msg.from_pid, &from);
+        // An empty target is filled in from the trash marks
+        self.start_storage_read(
+            &inode_key(&from),
+            PendingOp::RenameOp {
+                ctx: client_ctx,
+                from,
+                to: request.to.unwrap_or_default(),
+                perm_ctx,
+                stage: RenameStage::ReadingSource,
+                reply: RenameReply::Restore,
+            },
+        )
+    }
+
+    /// Start a list or purge of a user's trash for a client.
+    fn start_trash_walk(
+        &mut self,
+        msg: &Message,
+        user_id: UserId,
+        request: TrashRequest,
+    ) -> Result<(), AppError> {
+        let dir = trash_dir(user_id);
+        syscall::debug(&format!("VfsService: {:?} on {}", request, dir));
+
+        let perm_ctx = self.permission_context(msg.from_pid, &dir);
+        self.start_storage_read(
+            &inode_key(&dir),
+            PendingOp::TrashOp {
+                ctx: Some(self.client_context(msg)),
+                user_id,
+                perm_ctx,
+                request,
+                scan: TrashScan::default(),
+                stage: TrashStage::ReadingDir,
+            },
+        )
+    }
+
+    // =========================================================================
+    // Walk state machine
+    // =========================================================================
+
+    /// Handle trash walk result - dispatches based on stage
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle_trash_op_result(
+        &mut self,
+        client_ctx: Option<ClientContext>,
+        user_id: UserId,
+        perm_ctx: PermissionContext,
+        request: TrashRequest,
+        mut scan: TrashScan,
+        stage: TrashStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let background = client_ctx.is_none();
+        let result = match stage {
+            TrashStage::ReadingDir => {
+                let dir = trash_dir(user_id);
+                let checked = match result_type {
+                    ResultKind::ReadOk => match parse_inode(data) {
+                        Ok(inode) if !inode.is_directory() => Err(VfsError::NotADirectory),
+                        Ok(inode) => {
+                            let allowed = match request {
+                                TrashRequest::List => check_read(&inode, &perm_ctx),
+                                TrashRequest::Purge { .. } => check_write(&inode, &perm_ctx),
+                                TrashRequest::Expire { .. } => true,
+                            };
+                            if allowed {
+                                Ok(true)
+                            } else {
+                                syscall::debug(&format!(
+                                    "VfsService: Permission denied for {:?} on {}",
+                                    request, dir
+                                ));
+                                Err(VfsError::PermissionDenied)
+                            }
+                        }
+                        Err(e) => Err(VfsError::StorageError(format!(
+                            "Failed to parse inode: {}",
+                            e
+                        ))),
+                    },
+                    // Nothing has been trashed yet
+                    ResultKind::NotFound => Ok(false),
+                    _ => Err(storage_failure("Trash directory read", result_type)),
+                };
+                // Purging one entry needs no listing
+                let only = match &request {
+                    TrashRequest::Purge { id: Some(id) } => Some(trash_path(user_id, id)),
+                    _ => None,
+                };
+                match (checked, only) {
+                    (Ok(true), Some(path)) => {
+                        scan.queue.push(path);
+                        self.trash_next(client_ctx, user_id, perm_ctx, request, scan)
+                    }
+                    (Ok(true), None) => self.start_storage_list(
+                        &inode_key(&dir),
+                        PendingOp::TrashOp {
+                            ctx: client_ctx,
+                            user_id,
+                            perm_ctx,
+                            request,
+                            scan,
+                            stage: TrashStage::Listing,
+                        },
+                    ),
+                    (Ok(false), Some(path)) => {
+                        scan.fail(&path, VfsError::NotFound);
+                        self.finish_trash(client_ctx, user_id, request, scan)
+                    }
+                    (Ok(false), None) => self.finish_trash(client_ctx, user_id, request, scan),
+                    (Err(error), _) => {
+                        scan.fail(&dir, error);
+                        self.finish_trash(client_ctx, user_id, request, scan)
+                    }
+                }
+            }
+            TrashStage::Listing => {
+                match result_type {
+                    ResultKind::ListOk => match serde_json::from_slice::<Vec<String>>(data) {
+                        Ok(children) => scan.queue = children,
+                        Err(e) => scan.fail(
+                            &trash_dir(user_id),
+                            VfsError::StorageError(format!("Failed to parse child list: {}", e)),
+                        ),
+                    },
+                    ResultKind::NotFound => {}
+                    _ => scan.fail(&trash_dir(user_id), storage_failure("Trash list", result_type)),
+                }
+                self.trash_next(client_ctx, user_id, perm_ctx, request, scan)
+            }
+            TrashStage::ReadingEntry { path } => {
+                match result_type {
+                    ResultKind::ReadOk => match parse_inode(data) {
+                        Ok(inode) => {
+                            let entry = entry_of(&inode);
+                            let purge = match &request {
+                                TrashRequest::List => {
+                                    // Entries put here by hand are left out
+                                    scan.entries.extend(entry);
+                                    false
+                                }
+                                TrashRequest::Purge { .. } => true,
+                                TrashRequest::Expire { now } => entry.is_some_and(|entry| {
+                                    is_expired(entry.deleted_at, *now, DEFAULT_TRASH_MAX_AGE_MS)
+                                }),
+                            };
+                            if purge && !check_write(&inode, &perm_ctx) {
+                                scan.fail(&path, VfsError::PermissionDenied);
+                            } else if purge && inode.is_file() {
+                                let op = PendingOp::TrashOp {
+                                    ctx: client_ctx,
+                                    user_id,
+                                    perm_ctx,
+                                    request,
+                                    scan,
+                                    stage: TrashStage::DeletingContent {
+                                        path: path.clone(),
+                                        charge: Charge::of(&inode),
+                                    },
+                                };
+                                return self.start_content_delete(&path, op);
+                            } else if purge {
+                                // Everything inside goes first, the directory last
+                                scan.tree.steps = alloc::vec![
+                                    RmdirStep::Remove {
+                                        path: path.clone(),
+                                        is_file: false,
+                                        charge: None,
+                                    },
+                                    RmdirStep::List(path),
+                                ];
+                            }
+                        }
+                        Err(e) => scan.fail(
+                            &path,
+                            VfsError::StorageError(format!("Failed to parse inode: {}", e)),
+                        ),
+                    },
+                    // Gone since the listing, unless it never existed
+                    ResultKind::NotFound => {
+                        if matches!(request, TrashRequest::Purge { id: Some(_) }) {
+                            scan.fail(&path, VfsError::NotFound);
+                        }
+                    }
+                    _ => scan.fail(&path, storage_failure("Inode read", result_type)),
+                }
+                self.trash_next(client_ctx, user_id, perm_ctx, request, scan)
+            }
+            TrashStage::ListingTree { dir } => {
+                match result_type {
+                    ResultKind::ListOk => match serde_json::from_slice::<Vec<String>>(data) {
+                        Ok(children) => scan
+                            .tree
+                            .steps
+                            .extend(children.into_iter().map(RmdirStep::Visit)),
+                        Err(e) => scan.fail(
+                            &dir,
+                            VfsError::StorageError(format!("Failed to parse child list: {}", e)),
+                        ),
+                    },
+                    ResultKind::NotFound => {}
+                    _ => scan.fail(&dir, storage_failure("Child list", result_type)),
+                }
+                self.trash_next(client_ctx, user_id, perm_ctx, request, scan)
+            }
+            TrashStage::ReadingTreeEntry { path } => {
+                match result_type {
+                    ResultKind::ReadOk => match parse_inode(data) {
+                        Ok(inode) if !check_write(&inode, &perm_ctx) => {
+                            scan.fail(&path, VfsError::PermissionDenied)
+                        }
+                        Ok(inode) => {
+                            scan.tree.steps.push(RmdirStep::Remove {
+                                path: path.clone(),
+                                is_file: inode.is_file(),
+                                charge: Charge::of(&inode),
+                            });
+                            if inode.is_directory() {
+                                scan.tree.steps.push(RmdirStep::List(path));
+                            }
+                        }
+                        Err(e) => scan.fail(
+                            &path,
+                            VfsError::StorageError(format!("Failed to parse inode: {}", e)),
+                        ),
+                    },
+                    // Removed since the listing
+                    ResultKind::NotFound => {}
+                    _ => scan.fail(&path, storage_failure("Inode read", result_type)),
+                }
+                self.trash_next(client_ctx, user_id, perm_ctx, request, scan)
+            }
+            TrashStage::DeletingContent { path, charge } => match result_type {
+                ResultKind::WriteOk | ResultKind::NotFound => self.start_storage_delete(
+                    &inode_key(&path),
+                    PendingOp::TrashOp {
+                        ctx: client_ctx,
+                        user_id,
+                        perm_ctx,
+                        request,
+                        scan,
+                        stage: TrashStage::DeletingInode { path, charge },
+                    },
+                ),
+                _ => {
+                    scan.fail(&path, storage_failure("Content delete", result_type));
+                    self.trash_next(client_ctx, user_id, perm_ctx, request, scan)
+                }
+            },
+            TrashStage::DeletingInode { path, charge } => {
+                if result_type == ResultKind::WriteOk {
+                    self.quotas.release(charge);
+                    self.notify_watchers(VfsEventKind::Delete, &path, None);
+                    // What was inside a purged directory isn't counted
+                    if parent_path(&path) == trash_dir(user_id) {
+                        scan.purged += 1;
+                    }
+                } else {
+                    // The content is gone, so the inode is orphaned
+                    scan.fail(&path, storage_failure("Inode delete", result_type));
+                }
+                self.trash_next(client_ctx, user_id, perm_ctx, request, scan)
+            }
+        };
+        // A sweep step that couldn't start ends the sweep
+        if background && result.is_err() {
+            self.trash.running = false;
+        }
+        result
+    }
+
+    /// Take the next step of a directory being purged, else read the next
+    /// queued entry, or finish the walk.
+    fn trash_next(
+        &mut self,
+        client_ctx: Option<ClientContext>,
+        user_id: UserId,
+        perm_ctx: PermissionContext,
+        request: TrashRequest,
+        mut scan: TrashScan,
+    ) -> Result<(), AppError> {
+        let (key, stage) = loop {
+            let Some(step) = scan.tree.steps.pop() else {
+                match scan.queue.pop() {
+                    Some(path) => break (inode_key(&path), TrashStage::ReadingEntry { path }),
+                    None => return self.finish_trash(client_ctx, user_id, request, scan),
+                }
+            };
+            match step {
+                RmdirStep::Visit(path) => {
+                    break (inode_key(&path), TrashStage::ReadingTreeEntry { path })
+                }
+                RmdirStep::List(dir) => break (inode_key(&dir), TrashStage::ListingTree { dir }),
+                // Something under this entry is still there, so keep it
+                RmdirStep::Remove { path, .. }
+                    if scan.tree.failed.iter().any(|f| is_under(&f.path, &path)) => {}
+                RmdirStep::Remove {
+                    path,
+                    is_file: true,
+                    charge,
+                } => break (path.clone(), TrashStage::DeletingContent { path, charge }),
+                RmdirStep::Remove { path, charge, .. } => {
+                    break (inode_key(&path), TrashStage::DeletingInode { path, charge })
+                }
+            }
+        };
+
+        let (read, list) = match stage {
+            TrashStage::ReadingDir
+            | TrashStage::ReadingEntry { .. }
+            | TrashStage::ReadingTreeEntry { .. } => (true, false),
+            TrashStage::Listing | TrashStage::ListingTree { .. } => (false, true),
+            TrashStage::DeletingContent { .. } | TrashStage::DeletingInode { .. } => (false, false),
+        };
+        let content = matches!(stage, TrashStage::DeletingContent { .. });
+        let op = PendingOp::TrashOp {
+            ctx: client_ctx,
+            user_id,
+            perm_ctx,
+            request,
+            scan,
+            stage,
+        };
+        if read {
+            self.start_storage_read(&key, op)
+        } else if list {
+            self.start_storage_list(&key, op)
+        } else if content {
+            // Content goes with whatever its record holds
+            self.start_content_delete(&key, op)
+        } else {
+            self.start_storage_delete(&key, op)
+        }
+    }
+
+    /// Answer the walk's client, or move the sweep on to the next user.
+    fn finish_trash(
+        &mut self,
+        client_ctx: Option<ClientContext>,
+        user_id: UserId,
+        request: TrashRequest,
+        mut scan: TrashScan,
+    ) -> Result<(), AppError> {
+        let tag = request.response_tag();
+        match (request, client_ctx) {
+            (TrashRequest::List, Some(client_ctx)) => {
+                scan.entries.sort_by_key(|entry| Reverse(entry.deleted_at));
+                let result = match scan.error {
+                    Some(error) => Err(error),
+                    None => Ok(scan.entries),
+                };
+                self.send_response(&client_ctx, tag, &TrashListResponse { result })
+            }
+            (TrashRequest::Purge { .. }, Some(client_ctx)) => {
+                syscall::debug(&format!(
+                    "VfsService: purged {} entries from {}",
+                    scan.purged,
+                    trash_dir(user_id)
+                ));
+                let result = match scan.error {
+                    Some(error) => Err(error),
+                    None => Ok(scan.purged),
+                };
+                self.send_response(&client_ctx, tag, &TrashPurgeResponse { result })
+            }
+            _ => {
+                if scan.purged > 0 || scan.error.is_some() {
+                    syscall::debug(&format!(
+                        "VfsService: trash sweep purged {} old entries from {} (error: {:?})",
+                        scan.purged,
+                        trash_dir(user_id),
+                        scan.error
+                    ));
+                }
+                self.sweep_next_user();
+                Ok(())
+            }
+        }
+    }
+
+    // =========================================================================
+    // Background sweep
+    // =========================================================================
+
+    /// Start a sweep of every user's trash when one is due.
+    pub fn pump_trash_sweep(&mut self, now_ms: u64) {
+        if self.trash.running || self.drain.is_draining() {
+            return;
+        }
+        let due = *self
+            .trash
+            .next_sweep_ms
+            .get_or_insert(now_ms + TRASH_FIRST_SWEEP_MS);
+        if now_ms < due {
+            return;
+        }
+        self.trash.next_sweep_ms = Some(now_ms + TRASH_SWEEP_INTERVAL_MS);
+        self.trash.running = true;
+        if self
+            .start_storage_list(&inode_key("/home"), PendingOp::TrashSweepHomes)
+            .is_err()
+        {
+            self.trash.running = false;
+        }
+    }
+
+    /// Home directory list completed - walk each user's trash in turn.
+    pub fn handle_trash_sweep_homes_result(
+        &mut self,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        self.trash.users = match result_type {
+            ResultKind::ListOk => serde_json::from_slice::<Vec<String>>(data)
+                .map(|homes| homes.iter().filter_map(|home| extract_user_id(home)).collect())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        self.sweep_next_user();
+        Ok(())
+    }
+
+    /// Walk the next user's trash, or end the sweep.
+    fn sweep_next_user(&mut self) {
+        let Some(user_id) = self.trash.users.pop() else {
+            self.trash.running = false;
+            return;
+        };
+        let op = PendingOp::TrashOp {
+            ctx: None,
+            user_id,
+            perm_ctx: PermissionContext {
+                user_id: Some(user_id),
+                process_class: ProcessClass::System,
+            },
+            request: TrashRequest::Expire {
+                now: syscall::get_wallclock(),
+            },
+            scan: TrashScan::default(),
+            stage: TrashStage::ReadingDir,
+        };
+        if self.start_storage_read(&inode_key(&trash_dir(user_id)), op).is_err() {
+            self.trash.running = false;
+        }
+    }
+}
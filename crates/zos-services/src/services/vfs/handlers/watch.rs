@@ -0,0 +1,252 @@
+//! Watch handlers for VFS Service
+//!
+//! Handles: watch, unwatch, and change notification
+//!
+//! A watch covers a path and its direct children, or everything below it
+//! if recursive. Like handles, watches are per-process: IDs are keyed by
+//! the sender's PID, so a process can only remove its own. Read
+//! permission is checked once when the watch is set.
+//!
+//! Events are sent once a change is committed, as MSG_VFS_EVENT messages
+//! routed to the watcher's input endpoint. A watch outlives the path it
+//! names (a deleted file that is recreated is reported again) and is not
+//! checkpointed; clients must watch again after a VFS restart.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: watch registered for the calling process
+//! - **Acceptable partial failure**: an event that can't be delivered is
+//!   dropped; the change itself stands
+//! - **Forbidden**: Removing another process's watch, watching a path the
+//!   caller can't read
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppContext, AppError, Message};
+use zos_ipc::storage::ResultKind;
+use zos_vfs::core::is_under;
+use zos_vfs::ipc::{
+    vfs_msg, UnwatchRequest, UnwatchResponse, VfsEvent, VfsEventKind, WatchRequest, WatchResponse,
+};
+use zos_vfs::service::{check_read, PermissionContext};
+use zos_vfs::{parent_path, VfsError};
+
+use super::super::{inode_key, parse_inode, validate_path, ClientContext, PendingOp, VfsService};
+
+/// Maximum watches per process (Rule 11: resource limits)
+pub const MAX_WATCHES_PER_PROCESS: usize = 32;
+
+/// Maximum watches across all processes (Rule 11: resource limits)
+pub const MAX_WATCHES: usize = 2048;
+
+/// A watched path.
+#[derive(Clone, Debug)]
+pub struct Watch {
+    /// Path the watch was set on
+    pub path: String,
+    /// Whether changes below direct children are reported
+    pub recursive: bool,
+}
+
+impl Watch {
+    /// Whether a change to `path` is reported to this watch.
+    pub fn matches(&self, path: &str) -> bool {
+        path == self.path
+            || parent_path(path) == self.path
+            || (self.recursive && is_under(path, &self.path))
+    }
+}
+
+/// Active watches, keyed by (pid, id).
+#[derive(Default)]
+pub struct WatchTable {
+    watches: BTreeMap<(u32, u32), Watch>,
+    next_id: u32,
+}
+
+impl WatchTable {
+    /// Register a watch for `pid`, or `None` if a limit is reached.
+    pub fn add(&mut self, pid: u32, watch: Watch) -> Option<u32> {
+        if self.watches.len() >= MAX_WATCHES || self.count_for(pid) >= MAX_WATCHES_PER_PROCESS {
+            return None;
+        }
+        // ID 0 is never issued so a zeroed request can't remove a real watch
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        while self.watches.contains_key(&(pid, self.next_id)) {
+            self.next_id = self.next_id.wrapping_add(1).max(1);
+        }
+        self.watches.insert((pid, self.next_id), watch);
+        Some(self.next_id)
+    }
+
+    /// Remove a watch set by `pid`.
+    pub fn remove(&mut self, pid: u32, id: u32) -> Option<Watch> {
+        self.watches.remove(&(pid, id))
+    }
+
+    /// Number of watches `pid` has set.
+    pub fn count_for(&self, pid: u32) -> usize {
+        self.watches.range((pid, 0)..=(pid, u32::MAX)).count()
+    }
+
+    /// (pid, id) of every watch a change to `path` (or, for a rename,
+    /// `new_path`) is reported to.
+    pub fn matching(&self, path: &str, new_path: Option<&str>) -> Vec<(u32, u32)> {
+        self.watches
+            .iter()
+            .filter(|(_, watch)| watch.matches(path) || new_path.is_some_and(|p| watch.matches(p)))
+            .map(|(key, _)| *key)
+            .collect()
+    }
+}
+
+impl VfsService {
+    // =========================================================================
+    // Request handlers
+    // =========================================================================
+
+    /// Handle MSG_VFS_WATCH - start watching a path
+    pub fn handle_watch(&mut self, _ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
+        let request: WatchRequest = match serde_json::from_slice(&msg.data) {
+            Ok(r) => r,
+            Err(e) => {
+                let response = WatchResponse {
+                    result: Err(VfsError::InvalidRequest(format!(
+                        "Failed to parse request: {}",
+                        e
+                    ))),
+                };
+                return self.send_response_via_debug(
+                    msg.from_pid,
+                    vfs_msg::MSG_VFS_WATCH_RESPONSE,
+                    &response,
+                );
+            }
+        };
+
+        if let Err(reason) = validate_path(&request.path) {
+            let response = WatchResponse {
+                result: Err(VfsError::InvalidPath(String::from(reason))),
+            };
+            return self.send_response_via_debug(
+                msg.from_pid,
+                vfs_msg::MSG_VFS_WATCH_RESPONSE,
+                &response,
+            );
+        }
+
+        syscall::debug(&format!(
+            "VfsService: watch {} (recursive={})",
+            request.path, request.recursive
+        ));
+
+        let perm_ctx = self.permission_context(msg.from_pid, &request.path);
+        let client_ctx = self.client_context(msg);
+
+        self.start_storage_read(
+            &inode_key(&request.path),
+            PendingOp::WatchOp {
+                ctx: client_ctx,
+                path: request.path,
+                perm_ctx,
+                recursive: request.recursive,
+            },
+        )
+    }
+
+    /// Handle MSG_VFS_UNWATCH - remove a watch
+    pub fn handle_unwatch(&mut self, msg: &Message) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let result = match serde_json::from_slice::<UnwatchRequest>(&msg.data) {
+            Ok(request) => match self.watches.remove(msg.from_pid, request.id) {
+                Some(_) => Ok(()),
+                None => Err(VfsError::InvalidRequest(format!(
+                    "Unknown watch {}",
+                    request.id
+                ))),
+            },
+            Err(e) => Err(VfsError::InvalidRequest(format!(
+                "Failed to parse request: {}",
+                e
+            ))),
+        };
+        let response = UnwatchResponse { result };
+        self.send_response(&client_ctx, vfs_msg::MSG_VFS_UNWATCH_RESPONSE, &response)
+    }
+
+    // =========================================================================
+    // Result handlers
+    // =========================================================================
+
+    /// Handle watch inode result: check the path exists and is readable.
+    pub fn handle_watch_result(
+        &mut self,
+        client_ctx: &ClientContext,
+        path: &str,
+        perm_ctx: &PermissionContext,
+        recursive: bool,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        let result = match result_type {
+            ResultKind::ReadOk => match parse_inode(data) {
+                Ok(inode) if check_read(&inode, perm_ctx) => {
+                    let watch = Watch {
+                        path: path.to_string(),
+                        recursive,
+                    };
+                    self.watches
+                        .add(client_ctx.pid, watch)
+                        .ok_or(VfsError::QuotaExceeded)
+                }
+                Ok(_) => {
+                    syscall::debug(&format!(
+                        "VfsService: Permission denied for watch {} (pid={})",
+                        path, client_ctx.pid
+                    ));
+                    Err(VfsError::PermissionDenied)
+                }
+                Err(e) => Err(VfsError::StorageError(format!(
+                    "Failed to parse inode: {}",
+                    e
+                ))),
+            },
+            ResultKind::NotFound => Err(VfsError::NotFound),
+            _ => Err(VfsError::StorageError(format!(
+                "Inode read failed: {} ({})",
+                result_type as u8,
+                result_type.name()
+            ))),
+        };
+        let response = WatchResponse { result };
+        self.send_response(client_ctx, vfs_msg::MSG_VFS_WATCH_RESPONSE, &response)
+    }
+
+    // =========================================================================
+    // Notification
+    // =========================================================================
+
+    /// Send a change event to every watch covering `path` (or `new_path`).
+    pub fn notify_watchers(&self, kind: VfsEventKind, path: &str, new_path: Option<&str>) {
+        for (pid, watch_id) in self.watches.matching(path, new_path) {
+            let event = VfsEvent {
+                watch_id,
+                kind,
+                path: path.to_string(),
+                new_path: new_path.map(String::from),
+            };
+            if self
+                .send_response_via_debug(pid, vfs_msg::MSG_VFS_EVENT, &event)
+                .is_err()
+            {
+                syscall::debug(&format!(
+                    "VfsService: dropped {:?} event for {} (pid={}, watch={})",
+                    kind, path, pid, watch_id
+                ));
+            }
+        }
+    }
+}
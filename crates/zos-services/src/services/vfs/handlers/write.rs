@@ -2,6 +2,10 @@
 //!
 //! Handles: write, mkdir operations
 //!
+//! The file write state machine also completes opens that create a file and
+//! writes through handles (see `handles`); `WriteReply` says which response
+//! it ends with.
+//!
 //! # Safety Properties
 //!
 //! This module enforces the following security invariants:
@@ -16,23 +20,51 @@
 //!    content AND inode are committed. Content is written first, then inode.
 //!    If inode fails after content succeeds, we have orphan content (acceptable)
 //!    rather than an inode pointing to missing content (data loss).
+//!
+//! 4. **Quota before content**: A file write reserves its size against the
+//!    owner's quota before any content is stored, and undoes the reservation
+//!    if a later step fails.
+//!
+//! 5. **Sealed content**: A file with an owner has its content sealed under
+//!    the owner's content key (see `encryption`); quota and the inode size
+//!    count the plaintext.
+//!
+//! 6. **Chunks before manifest**: Content larger than one chunk is stored
+//!    as chunks under a new content id, and the manifest naming them is
+//!    the content write. The chunks of the file it replaces are deleted
+//!    only once that manifest has landed (see `chunks`).
+//!
+//! 7. **Writes never reach shared content**: A file that is linked to
+//!    another gets a content record of its own, and its reference to the
+//!    shared blob is dropped once that record has landed (see `link`).
+//!
+//! 8. **Hashed content**: The new inode records the SHA-256 of the
+//!    plaintext, which reads and `MSG_VFS_HASH` check the content against
+//!    (see `hash`).
 
 use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
 use zos_apps::syscall;
 use zos_apps::{AppContext, AppError, Message};
-use zos_process::storage_result;
-use zos_vfs::ipc::{vfs_msg, MkdirRequest, MkdirResponse, WriteFileRequest, WriteFileResponse};
+use zos_flags::FLAGS_PATH;
+use zos_ipc::storage::ResultKind;
+use zos_vfs::ipc::{
+    vfs_msg, MkdirRequest, MkdirResponse, OpenResponse, VfsEventKind, WriteAtResponse,
+    WriteFileRequest, WriteFileResponse,
+};
 use zos_vfs::service::{check_write, PermissionContext};
-use zos_vfs::Inode;
+use zos_vfs::storage::chunking::{is_chunked, ChunkManifest};
+use zos_vfs::storage::integrity::{content_hash, ContentHash};
+use zos_vfs::{Inode, Xattrs};
 use zos_vfs::{parent_path, VfsError};
 
 use super::super::{
-    build_parent_paths, content_key, derive_permission_context, inode_key, result_type_name,
-    validate_path, ClientContext, MkdirStage, PendingOp, VfsService, WriteFileStage,
-    MAX_CONTENT_SIZE,
+    build_parent_paths, content_key, inode_key, parse_inode, validate_path, Charge, ClientContext,
+    MkdirStage, PendingOp, Reservation, VfsService, WriteFileStage, WriteReply, MAX_CONTENT_SIZE,
 };
+use super::chunks::new_content_id;
+use super::link::HeldContent;
 
 impl VfsService {
     // =========================================================================
@@ -47,6 +79,26 @@ impl VfsService {
         self.send_response(client_ctx, vfs_msg::MSG_VFS_WRITE_RESPONSE, &response)
     }
 
+    /// Send the failure of a file write to whoever the write answers.
+    fn send_write_failure(
+        &self,
+        client_ctx: &ClientContext,
+        reply: &WriteReply,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        match reply {
+            WriteReply::Write => self.send_write_error(client_ctx, error),
+            WriteReply::Open { .. } => {
+                let response = OpenResponse { result: Err(error) };
+                self.send_response(client_ctx, vfs_msg::MSG_VFS_OPEN_RESPONSE, &response)
+            }
+            WriteReply::WriteAt { .. } => {
+                let response = WriteAtResponse { result: Err(error) };
+                self.send_response(client_ctx, vfs_msg::MSG_VFS_WRITE_AT_RESPONSE, &response)
+            }
+        }
+    }
+
     /// Send a write error response via debug channel (when no ClientContext available).
     fn send_write_error_via_debug(&self, to_pid: u32, error: VfsError) -> Result<(), AppError> {
         let response = WriteFileResponse {
@@ -79,9 +131,10 @@ impl VfsService {
     ///
     /// This starts the write file state machine:
     /// 1. Check parent exists and is directory, check permissions
-    /// 2. Write content first
-    /// 3. Write inode (only after content succeeds)
-    /// 4. Send response (only after inode succeeds)
+    /// 2. Read the inode being replaced and reserve the owner's quota
+    /// 3. Write content first
+    /// 4. Write inode (only after content succeeds)
+    /// 5. Send response (only after inode succeeds)
     pub fn handle_write(&mut self, _ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
         // Parse request
         let request: WriteFileRequest = match serde_json::from_slice(&msg.data) {
@@ -129,8 +182,8 @@ impl VfsService {
         ));
 
         // Derive permission context from caller
-        let perm_ctx = derive_permission_context(msg.from_pid, &request.path);
-        let client_ctx = ClientContext::from_message(msg);
+        let perm_ctx = self.permission_context(msg.from_pid, &request.path);
+        let client_ctx = self.client_context(msg);
 
         // Use inode/content pattern for VFS operations
         let parent = parent_path(&request.path);
@@ -145,6 +198,7 @@ impl VfsService {
                 stage: WriteFileStage::CheckingParent {
                     content: request.content,
                 },
+                reply: WriteReply::Write,
             },
         )
     }
@@ -176,8 +230,8 @@ impl VfsService {
         ));
 
         // Derive permission context from caller (for parent directory check)
-        let perm_ctx = derive_permission_context(msg.from_pid, &request.path);
-        let client_ctx = ClientContext::from_message(msg);
+        let perm_ctx = self.permission_context(msg.from_pid, &request.path);
+        let client_ctx = self.client_context(msg);
 
         // First check if already exists using dedicated exists check
         self.start_storage_exists(
@@ -204,19 +258,19 @@ impl VfsService {
         client_ctx: &ClientContext,
         path: &str,
         _create_parents: bool,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         // Handle result type strictly
         match result_type {
-            storage_result::EXISTS_OK => {
+            ResultKind::ExistsOk => {
                 let exists = !data.is_empty() && data[0] == 1;
                 if exists {
                     return self.send_mkdir_error(client_ctx, VfsError::AlreadyExists);
                 }
                 // Path doesn't exist - proceed to create
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 // Key not found = doesn't exist, proceed to create
             }
             _ => {
@@ -224,15 +278,15 @@ impl VfsService {
                 syscall::debug(&format!(
                     "VfsService: mkdir {} exists check failed with unexpected result: {} ({})",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 return self.send_mkdir_error(
                     client_ctx,
                     VfsError::StorageError(format!(
                         "Exists check failed: unexpected result type {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )),
                 );
             }
@@ -272,22 +326,22 @@ impl VfsService {
         &mut self,
         client_ctx: &ClientContext,
         path: &str,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         // Handle result type strictly
         match result_type {
-            storage_result::EXISTS_OK => {
+            ResultKind::ExistsOk => {
                 let exists = !data.is_empty() && data[0] == 1;
                 if exists {
                     return self.send_mkdir_error(client_ctx, VfsError::AlreadyExists);
                 }
                 // Proceed to create
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 // Doesn't exist - proceed to create
             }
-            storage_result::READ_OK => {
+            ResultKind::ReadOk => {
                 // Path exists (we got data) - this shouldn't happen with exists check
                 // but handle it gracefully
                 return self.send_mkdir_error(client_ctx, VfsError::AlreadyExists);
@@ -297,15 +351,15 @@ impl VfsService {
                 syscall::debug(&format!(
                     "VfsService: mkdir {} failed with unexpected result: {} ({})",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 return self.send_mkdir_error(
                     client_ctx,
                     VfsError::StorageError(format!(
                         "Check exists failed: unexpected result type {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )),
                 );
             }
@@ -341,74 +395,165 @@ impl VfsService {
     ///
     /// This handler implements the write file state machine:
     /// 1. CheckingParent: Verify parent exists, is directory, check permissions
-    /// 2. WritingContent: Content write completed, now write inode
-    /// 3. WritingInode: Inode write completed, send success response
+    /// 2. ReadingExisting: Reserve the size change against the owner's quota
+    ///    (ReadingReplaced first finds the chunks or blob of the file it
+    ///    replaces)
+    /// 3. WritingChunks: Chunk stored, store the next or the manifest
+    /// 4. WritingContent: Content write completed, now write inode
+    /// 5. WritingInode: Inode write completed, send success response
+    #[allow(clippy::too_many_arguments)]
     pub fn handle_write_file_op_result(
         &mut self,
         client_ctx: &ClientContext,
         path: &str,
         perm_ctx: &PermissionContext,
         stage: WriteFileStage,
-        result_type: u8,
+        reply: WriteReply,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         match stage {
-            WriteFileStage::CheckingParent { content } => {
-                self.handle_write_checking_parent(client_ctx, path, perm_ctx, result_type, data, content)
+            WriteFileStage::CheckingParent { content } => self.handle_write_checking_parent(
+                client_ctx, path, perm_ctx, reply, result_type, data, content,
+            ),
+            WriteFileStage::ReadingExisting { content } => self.handle_write_reading_existing(
+                client_ctx, path, perm_ctx, reply, result_type, data, content,
+            ),
+            WriteFileStage::ReadingReplaced {
+                content,
+                released,
+                xattrs,
+            } => {
+                // Only a readable manifest or link holds anything to release
+                let replaced = (result_type == ResultKind::ReadOk)
+                    .then(|| HeldContent::of(data).ok().flatten())
+                    .flatten();
+                self.store_write_content(
+                    client_ctx, path, perm_ctx, reply, content, released, replaced, xattrs,
+                )
             }
-            WriteFileStage::WritingContent { content_len } => {
-                self.handle_write_content_done(client_ctx, path, perm_ctx, content_len, result_type)
+            WriteFileStage::WritingChunks {
+                content,
+                manifest,
+                next,
+                reservation,
+                replaced,
+                xattrs,
+            } => {
+                if result_type != ResultKind::WriteOk {
+                    self.quotas.undo(&reservation);
+                    self.release_stored_chunks(manifest, next);
+                    return self.send_write_failure(
+                        client_ctx,
+                        &reply,
+                        VfsError::StorageError(format!(
+                            "Chunk write failed: {} ({})",
+                            result_type as u8,
+                            result_type.name()
+                        )),
+                    );
+                }
+                self.write_next_chunk(
+                    client_ctx,
+                    path,
+                    perm_ctx,
+                    reply,
+                    content,
+                    manifest,
+                    next + 1,
+                    reservation,
+                    replaced,
+                    xattrs,
+                )
             }
-            WriteFileStage::WritingInode => {
-                self.handle_write_inode_done(client_ctx, path, result_type)
+            WriteFileStage::WritingContent {
+                content_len,
+                encrypted,
+                content_hash,
+                reservation,
+                chunks,
+                replaced,
+                xattrs,
+            } => {
+                match (result_type, chunks, replaced) {
+                    // The old content record is gone, and with it the last
+                    // reference to its chunks
+                    (ResultKind::WriteOk, _, Some(replaced)) => self.release_held(replaced),
+                    (ResultKind::WriteOk, _, None) => {}
+                    (_, Some(chunks), _) => self.start_chunk_release(chunks),
+                    (_, None, _) => {}
+                }
+                self.handle_write_content_done(
+                    client_ctx,
+                    path,
+                    perm_ctx,
+                    reply,
+                    content_len,
+                    encrypted,
+                    content_hash,
+                    reservation,
+                    xattrs,
+                    result_type,
+                )
             }
+            WriteFileStage::WritingInode { reservation } => self.handle_write_inode_done(
+                client_ctx,
+                path,
+                perm_ctx,
+                reply,
+                reservation,
+                result_type,
+            ),
         }
     }
 
     /// Stage 1: Check parent directory exists, is a directory, and we have permission
+    #[allow(clippy::too_many_arguments)]
     fn handle_write_checking_parent(
         &mut self,
         client_ctx: &ClientContext,
         path: &str,
         perm_ctx: &PermissionContext,
-        result_type: u8,
+        reply: WriteReply,
+        result_type: ResultKind,
         data: &[u8],
         content: Vec<u8>,
     ) -> Result<(), AppError> {
         // Handle result type strictly - only READ_OK is acceptable for parent check
         match result_type {
-            storage_result::READ_OK => {
+            ResultKind::ReadOk => {
                 // Good - parse and validate parent
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 syscall::debug(&format!(
                     "VfsService: write {} failed - parent directory not found",
                     path
                 ));
                 // More specific error: parent doesn't exist
-                return self.send_write_error(client_ctx, VfsError::NotFound);
+                return self.send_write_failure(client_ctx, &reply, VfsError::NotFound);
             }
             _ => {
                 // Unexpected result type - fail closed
                 syscall::debug(&format!(
                     "VfsService: write {} parent check failed with unexpected result: {} ({})",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
-                return self.send_write_error(
+                return self.send_write_failure(
                     client_ctx,
+                    &reply,
                     VfsError::StorageError(format!(
                         "Parent read failed: unexpected result type {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )),
                 );
             }
         }
 
         // Parse parent inode - FAIL CLOSED on parse error
-        let parent_inode = match serde_json::from_slice::<Inode>(data) {
+        let parent_inode = match parse_inode(data) {
             Ok(inode) => inode,
             Err(e) => {
                 // SECURITY: Fail closed - corrupt/malicious parent blob could bypass permission check
@@ -416,8 +561,9 @@ impl VfsService {
                     "VfsService: SECURITY: Failed to parse parent inode for {}: {} (denying write)",
                     path, e
                 ));
-                return self.send_write_error(
+                return self.send_write_failure(
                     client_ctx,
+                    &reply,
                     VfsError::StorageError(format!(
                         "Parent inode corrupt or invalid: {}",
                         e
@@ -432,7 +578,7 @@ impl VfsService {
                 "VfsService: write {} failed - parent is not a directory (type: {:?})",
                 path, parent_inode.inode_type
             ));
-            return self.send_write_error(client_ctx, VfsError::NotADirectory);
+            return self.send_write_failure(client_ctx, &reply, VfsError::NotADirectory);
         }
 
         // Check write permission on parent directory
@@ -441,47 +587,293 @@ impl VfsService {
                 "VfsService: Permission denied for write {} (pid={})",
                 path, client_ctx.pid
             ));
-            return self.send_write_error(client_ctx, VfsError::PermissionDenied);
+            return self.send_write_failure(client_ctx, &reply, VfsError::PermissionDenied);
         }
 
-        // Permission granted - write content FIRST
-        // This ensures we never have an inode pointing to missing content
+        // Permission granted - read the file being replaced so its size can
+        // be credited back before the new one is charged
+        self.start_storage_read(
+            &inode_key(path),
+            PendingOp::WriteFileOp {
+                ctx: client_ctx.clone(),
+                path: path.to_string(),
+                perm_ctx: perm_ctx.clone(),
+                stage: WriteFileStage::ReadingExisting { content },
+                reply,
+            },
+        )
+    }
+
+    /// Stage 2: Existing inode read - reserve quota, then write content
+    #[allow(clippy::too_many_arguments)]
+    fn handle_write_reading_existing(
+        &mut self,
+        client_ctx: &ClientContext,
+        path: &str,
+        perm_ctx: &PermissionContext,
+        reply: WriteReply,
+        result_type: ResultKind,
+        data: &[u8],
+        content: Vec<u8>,
+    ) -> Result<(), AppError> {
+        let (released, is_file, xattrs) = match result_type {
+            ResultKind::ReadOk => match parse_inode(data) {
+                // A file keeps its extended attributes when rewritten
+                Ok(existing) if existing.is_file() => (Charge::of(&existing), true, existing.xattrs),
+                Ok(existing) => (Charge::of(&existing), false, Xattrs::new()),
+                Err(e) => {
+                    // Fail closed: without the old size the usage can't be kept right
+                    syscall::debug(&format!(
+                        "VfsService: write {} failed to parse existing inode: {}",
+                        path, e
+                    ));
+                    return self.send_write_failure(
+                        client_ctx,
+                        &reply,
+                        VfsError::StorageError(format!("Existing inode corrupt or invalid: {}", e)),
+                    );
+                }
+            },
+            ResultKind::NotFound => (None, false, Xattrs::new()),
+            _ => {
+                return self.send_write_failure(
+                    client_ctx,
+                    &reply,
+                    VfsError::StorageError(format!(
+                        "Existing inode read failed: {} ({})",
+                        result_type as u8,
+                        result_type.name()
+                    )),
+                );
+            }
+        };
+
+        // The record being replaced may be a manifest or a link, which say
+        // what to release once the new content replaces it
+        if is_file {
+            return self.start_storage_read(
+                &content_key(path),
+                PendingOp::WriteFileOp {
+                    ctx: client_ctx.clone(),
+                    path: path.to_string(),
+                    perm_ctx: perm_ctx.clone(),
+                    stage: WriteFileStage::ReadingReplaced {
+                        content,
+                        released,
+                        xattrs,
+                    },
+                    reply,
+                },
+            );
+        }
+        self.store_write_content(client_ctx, path, perm_ctx, reply, content, released, None, xattrs)
+    }
+
+    /// Reserve quota for the new content, then store it: as one record, or
+    /// as chunks followed by their manifest.
+    #[allow(clippy::too_many_arguments)]
+    fn store_write_content(
+        &mut self,
+        client_ctx: &ClientContext,
+        path: &str,
+        perm_ctx: &PermissionContext,
+        reply: WriteReply,
+        content: Vec<u8>,
+        released: Option<Charge>,
+        replaced: Option<HeldContent>,
+        xattrs: Xattrs,
+    ) -> Result<(), AppError> {
         let content_len = content.len() as u64;
-        self.start_storage_write(
+        let added = perm_ctx
+            .user_id
+            .filter(|_| content_len > 0)
+            .map(|user_id| Charge {
+                user_id,
+                bytes: content_len,
+            });
+        let reservation = match self.quotas.reserve(added, released) {
+            Ok(reservation) => reservation,
+            Err(error) => {
+                syscall::debug(&format!(
+                    "VfsService: write {} refused by quota: {:?}",
+                    path, error
+                ));
+                return self.send_write_failure(client_ctx, &reply, error);
+            }
+        };
+
+        if is_chunked(content_len) {
+            let manifest = match new_content_id() {
+                Ok(id) => ChunkManifest::new(id, content_len),
+                Err(error) => {
+                    self.quotas.undo(&reservation);
+                    return self.send_write_failure(client_ctx, &reply, error);
+                }
+            };
+            return self.write_next_chunk(
+                client_ctx,
+                path,
+                perm_ctx,
+                reply,
+                content,
+                manifest,
+                0,
+                reservation,
+                replaced,
+                xattrs,
+            );
+        }
+
+        // Hashed before sealing: the inode records the plaintext's hash
+        let content_hash = content_hash(&content);
+
+        // Owned content is stored sealed under the owner's key, which the
+        // dispatcher has loaded before handing us this result
+        let blob = match self.seal_content(perm_ctx.user_id, content) {
+            Ok(blob) => blob,
+            Err(error) => {
+                syscall::debug(&format!(
+                    "VfsService: write {} cannot be sealed: {:?}",
+                    path, error
+                ));
+                self.quotas.undo(&reservation);
+                return self.send_write_failure(client_ctx, &reply, error);
+            }
+        };
+
+        // Write content FIRST
+        // This ensures we never have an inode pointing to missing content
+        let started = self.start_storage_write(
             &content_key(path),
-            &content,
+            &blob,
             PendingOp::WriteFileOp {
                 ctx: client_ctx.clone(),
                 path: path.to_string(),
                 perm_ctx: perm_ctx.clone(),
-                stage: WriteFileStage::WritingContent { content_len },
+                stage: WriteFileStage::WritingContent {
+                    content_len,
+                    encrypted: perm_ctx.user_id.is_some(),
+                    content_hash,
+                    reservation,
+                    chunks: None,
+                    replaced,
+                    xattrs,
+                },
+                reply,
             },
-        )
+        );
+        if started.is_err() {
+            self.quotas.undo(&reservation);
+        }
+        started
+    }
+
+    /// Store chunk `index` of `content`, or the manifest once every chunk
+    /// is stored.
+    #[allow(clippy::too_many_arguments)]
+    fn write_next_chunk(
+        &mut self,
+        client_ctx: &ClientContext,
+        path: &str,
+        perm_ctx: &PermissionContext,
+        reply: WriteReply,
+        content: Vec<u8>,
+        manifest: ChunkManifest,
+        index: u32,
+        reservation: Reservation,
+        replaced: Option<HeldContent>,
+        xattrs: Xattrs,
+    ) -> Result<(), AppError> {
+        let (key, value, stage) = if index == manifest.chunk_count() {
+            let stage = WriteFileStage::WritingContent {
+                content_len: manifest.size,
+                encrypted: perm_ctx.user_id.is_some(),
+                content_hash: content_hash(&content),
+                reservation,
+                chunks: Some(manifest),
+                replaced,
+                xattrs,
+            };
+            (content_key(path), manifest.encode(), stage)
+        } else {
+            let chunk = manifest.chunk_of(&content, index).to_vec();
+            let blob = match self.seal_content(perm_ctx.user_id, chunk) {
+                Ok(blob) => blob,
+                Err(error) => {
+                    self.quotas.undo(&reservation);
+                    self.release_stored_chunks(manifest, index);
+                    return self.send_write_failure(client_ctx, &reply, error);
+                }
+            };
+            let stage = WriteFileStage::WritingChunks {
+                content,
+                manifest,
+                next: index,
+                reservation,
+                replaced,
+                xattrs,
+            };
+            (manifest.chunk_key(index), blob, stage)
+        };
+
+        let started = self.start_storage_write(
+            &key,
+            &value,
+            PendingOp::WriteFileOp {
+                ctx: client_ctx.clone(),
+                path: path.to_string(),
+                perm_ctx: perm_ctx.clone(),
+                stage,
+                reply,
+            },
+        );
+        if started.is_err() {
+            self.quotas.undo(&reservation);
+            self.release_stored_chunks(manifest, index);
+        }
+        started
+    }
+
+    /// Delete the first `stored` chunks of a write that failed before its
+    /// manifest landed; nothing names them.
+    fn release_stored_chunks(&mut self, manifest: ChunkManifest, stored: u32) {
+        self.start_chunk_release(ChunkManifest {
+            size: manifest.chunk_start(stored).min(manifest.size),
+            ..manifest
+        });
     }
 
-    /// Stage 2: Content write completed - now write inode
+    /// Stage 3: Content write completed - now write inode
+    #[allow(clippy::too_many_arguments)]
     fn handle_write_content_done(
         &mut self,
         client_ctx: &ClientContext,
         path: &str,
         perm_ctx: &PermissionContext,
+        reply: WriteReply,
         content_len: u64,
-        result_type: u8,
+        encrypted: bool,
+        content_hash: ContentHash,
+        reservation: Reservation,
+        xattrs: Xattrs,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
         // Content write must succeed before we write inode
-        if result_type != storage_result::WRITE_OK {
+        if result_type != ResultKind::WriteOk {
+            self.quotas.undo(&reservation);
             syscall::debug(&format!(
                 "VfsService: write {} content write failed: {} ({})",
                 path,
-                result_type,
-                result_type_name(result_type)
+                result_type as u8,
+                result_type.name()
             ));
-            return self.send_write_error(
+            return self.send_write_failure(
                 client_ctx,
+                &reply,
                 VfsError::StorageError(format!(
                     "Content write failed: {} ({})",
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 )),
             );
         }
@@ -494,15 +886,17 @@ impl VfsService {
         // Set owner_id based on permission context (user writes own their files)
         let owner_id = perm_ctx.user_id;
 
-        let inode = Inode::new_file(
+        let mut inode = Inode::new_file(
             path.to_string(),
             parent,
             name,
             owner_id,
             content_len,
-            None, // TODO: compute content hash
+            Some(content_hash),
             now,
         );
+        inode.encrypted = encrypted;
+        inode.xattrs = xattrs;
 
         let inode_json = match serde_json::to_vec(&inode) {
             Ok(j) => j,
@@ -513,55 +907,88 @@ impl VfsService {
                     "VfsService: write {} inode serialization failed after content write: {}",
                     path, e
                 ));
-                return self.send_write_error(
+                self.quotas.undo(&reservation);
+                return self.send_write_failure(
                     client_ctx,
+                    &reply,
                     VfsError::StorageError(format!("Failed to serialize inode: {}", e)),
                 );
             }
         };
 
         // Write inode (stage 2)
-        self.start_storage_write(
+        let started = self.start_storage_write(
             &inode_key(path),
             &inode_json,
             PendingOp::WriteFileOp {
                 ctx: client_ctx.clone(),
                 path: path.to_string(),
                 perm_ctx: perm_ctx.clone(),
-                stage: WriteFileStage::WritingInode,
+                stage: WriteFileStage::WritingInode { reservation },
+                reply,
             },
-        )
+        );
+        if started.is_err() {
+            self.quotas.undo(&reservation);
+        }
+        started
     }
 
-    /// Stage 3: Inode write completed - send response
+    /// Stage 4: Inode write completed - send response
     fn handle_write_inode_done(
         &mut self,
         client_ctx: &ClientContext,
         path: &str,
-        result_type: u8,
+        perm_ctx: &PermissionContext,
+        reply: WriteReply,
+        reservation: Reservation,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
-        if result_type != storage_result::WRITE_OK {
+        if result_type != ResultKind::WriteOk {
+            // The old inode is still the one stored, so its charge stands
+            self.quotas.undo(&reservation);
             // Inode write failed - content is orphaned but that's acceptable
             syscall::debug(&format!(
                 "VfsService: write {} inode write failed: {} ({}) - content is orphaned",
                 path,
-                result_type,
-                result_type_name(result_type)
+                result_type as u8,
+                result_type.name()
             ));
-            return self.send_write_error(
+            return self.send_write_failure(
                 client_ctx,
+                &reply,
                 VfsError::StorageError(format!(
                     "Inode write failed: {} ({})",
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 )),
             );
         }
 
         // Both content and inode written successfully
         syscall::debug(&format!("VfsService: write {} completed successfully", path));
-        let response = WriteFileResponse { result: Ok(()) };
-        self.send_response(client_ctx, vfs_msg::MSG_VFS_WRITE_RESPONSE, &response)
+        let kind = match reply {
+            WriteReply::Open { .. } => VfsEventKind::Create,
+            WriteReply::Write | WriteReply::WriteAt { .. } => VfsEventKind::Write,
+        };
+        self.notify_watchers(kind, path, None);
+        if path == FLAGS_PATH {
+            // The flag store changed; pick up vfs.verify_hashes
+            self.start_flags_load();
+        }
+        match reply {
+            WriteReply::Write => {
+                let response = WriteFileResponse { result: Ok(()) };
+                self.send_response(client_ctx, vfs_msg::MSG_VFS_WRITE_RESPONSE, &response)
+            }
+            WriteReply::Open { writable } => {
+                self.open_handle(client_ctx, path, perm_ctx, writable)
+            }
+            WriteReply::WriteAt { size } => {
+                let response = WriteAtResponse { result: Ok(size) };
+                self.send_response(client_ctx, vfs_msg::MSG_VFS_WRITE_AT_RESPONSE, &response)
+            }
+        }
     }
 
     /// Handle write file inode result (checking parent exists and permissions)
@@ -573,12 +1000,20 @@ impl VfsService {
         client_ctx: &ClientContext,
         path: &str,
         perm_ctx: &PermissionContext,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
         content: Vec<u8>,
     ) -> Result<(), AppError> {
         // Redirect to the new state machine implementation
-        self.handle_write_checking_parent(client_ctx, path, perm_ctx, result_type, data, content)
+        self.handle_write_checking_parent(
+            client_ctx,
+            path,
+            perm_ctx,
+            WriteReply::Write,
+            result_type,
+            data,
+            content,
+        )
     }
 
     /// Handle put inode result
@@ -588,7 +1023,7 @@ impl VfsService {
         &mut self,
         client_ctx: Option<&ClientContext>,
         response_tag: u32,
-        result_type: u8,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
         // If no client context, this is an intermediate step - no response needed
         let client_ctx = match client_ctx {
@@ -596,7 +1031,7 @@ impl VfsService {
             None => return Ok(()),
         };
 
-        let success = result_type == storage_result::WRITE_OK;
+        let success = result_type == ResultKind::WriteOk;
 
         if response_tag == vfs_msg::MSG_VFS_MKDIR_RESPONSE {
             let response = MkdirResponse {
@@ -605,8 +1040,8 @@ impl VfsService {
                 } else {
                     Err(VfsError::StorageError(format!(
                         "Mkdir inode write failed: {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )))
                 },
             };
@@ -625,21 +1060,21 @@ impl VfsService {
         &mut self,
         client_ctx: &ClientContext,
         path: &str,
-        result_type: u8,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
-        if result_type != storage_result::WRITE_OK {
+        if result_type != ResultKind::WriteOk {
             syscall::debug(&format!(
                 "VfsService: write {} content write failed: {} ({})",
                 path,
-                result_type,
-                result_type_name(result_type)
+                result_type as u8,
+                result_type.name()
             ));
             return self.send_write_error(
                 client_ctx,
                 VfsError::StorageError(format!(
                     "Content write failed: {} ({})",
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 )),
             );
         }
@@ -668,7 +1103,7 @@ impl VfsService {
         perm_ctx: &PermissionContext,
         stage: MkdirStage,
         create_parents: bool,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         match stage {
@@ -698,12 +1133,12 @@ impl VfsService {
         path: &str,
         perm_ctx: &PermissionContext,
         create_parents: bool,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         // Handle result type strictly
         match result_type {
-            storage_result::EXISTS_OK => {
+            ResultKind::ExistsOk => {
                 let exists = !data.is_empty() && data[0] == 1;
                 if exists {
                     // For create_parents=true (mkdir -p behavior), existing directory is success
@@ -719,7 +1154,7 @@ impl VfsService {
                 }
                 // Path doesn't exist - proceed
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 // Key not found = doesn't exist, proceed
             }
             _ => {
@@ -727,15 +1162,15 @@ impl VfsService {
                 syscall::debug(&format!(
                     "VfsService: mkdir {} exists check failed with unexpected result: {} ({})",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 return self.send_mkdir_error(
                     client_ctx,
                     VfsError::StorageError(format!(
                         "Exists check failed: unexpected result type {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )),
                 );
             }
@@ -794,15 +1229,15 @@ impl VfsService {
         path: &str,
         perm_ctx: &PermissionContext,
         create_parents: bool,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         // Handle result type strictly - only READ_OK is acceptable for parent check
         match result_type {
-            storage_result::READ_OK => {
+            ResultKind::ReadOk => {
                 // Good - parse and validate parent
             }
-            storage_result::NOT_FOUND => {
+            ResultKind::NotFound => {
                 syscall::debug(&format!(
                     "VfsService: mkdir {} failed - parent directory not found",
                     path
@@ -814,22 +1249,22 @@ impl VfsService {
                 syscall::debug(&format!(
                     "VfsService: mkdir {} parent check failed with unexpected result: {} ({})",
                     path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 return self.send_mkdir_error(
                     client_ctx,
                     VfsError::StorageError(format!(
                         "Parent read failed: unexpected result type {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )),
                 );
             }
         }
 
         // Parse parent inode - FAIL CLOSED on parse error
-        let parent_inode = match serde_json::from_slice::<Inode>(data) {
+        let parent_inode = match parse_inode(data) {
             Ok(inode) => inode,
             Err(e) => {
                 // SECURITY: Fail closed - corrupt/malicious parent blob could bypass permission check
@@ -917,28 +1352,28 @@ impl VfsService {
         perm_ctx: &PermissionContext,
         paths: Vec<String>,
         index: usize,
-        result_type: u8,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
         let current_path = &paths[index];
         
         // Check the result of the exists check
         let exists = match result_type {
-            storage_result::EXISTS_OK => true,
-            storage_result::NOT_FOUND => false,
+            ResultKind::ExistsOk => true,
+            ResultKind::NotFound => false,
             _ => {
                 syscall::debug(&format!(
                     "VfsService: mkdir {} parent check failed for {}: unexpected result {} ({})",
                     target_path,
                     current_path,
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 ));
                 return self.send_mkdir_error(
                     client_ctx,
                     VfsError::StorageError(format!(
                         "Parent check failed: unexpected result type {} ({})",
-                        result_type,
-                        result_type_name(result_type)
+                        result_type as u8,
+                        result_type.name()
                     )),
                 );
             }
@@ -1031,25 +1466,27 @@ impl VfsService {
         perm_ctx: &PermissionContext,
         paths: Vec<String>,
         index: usize,
-        result_type: u8,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
-        if result_type != storage_result::WRITE_OK {
+        if result_type != ResultKind::WriteOk {
             syscall::debug(&format!(
                 "VfsService: mkdir {} parent directory write failed: {} ({})",
                 target_path,
-                result_type,
-                result_type_name(result_type)
+                result_type as u8,
+                result_type.name()
             ));
             return self.send_mkdir_error(
                 client_ctx,
                 VfsError::StorageError(format!(
                     "Parent directory write failed: {} ({})",
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 )),
             );
         }
-        
+
+        self.notify_watchers(VfsEventKind::Create, &paths[index], None);
+
         // Move to next path
         self.mkdir_creating_parents_next(client_ctx, target_path, perm_ctx, paths, index)
     }
@@ -1059,26 +1496,27 @@ impl VfsService {
         &mut self,
         client_ctx: &ClientContext,
         path: &str,
-        result_type: u8,
+        result_type: ResultKind,
     ) -> Result<(), AppError> {
-        if result_type != storage_result::WRITE_OK {
+        if result_type != ResultKind::WriteOk {
             syscall::debug(&format!(
                 "VfsService: mkdir {} inode write failed: {} ({})",
                 path,
-                result_type,
-                result_type_name(result_type)
+                result_type as u8,
+                result_type.name()
             ));
             return self.send_mkdir_error(
                 client_ctx,
                 VfsError::StorageError(format!(
                     "Inode write failed: {} ({})",
-                    result_type,
-                    result_type_name(result_type)
+                    result_type as u8,
+                    result_type.name()
                 )),
             );
         }
 
         syscall::debug(&format!("VfsService: mkdir {} completed successfully", path));
+        self.notify_watchers(VfsEventKind::Create, path, None);
         let response = MkdirResponse { result: Ok(()) };
         self.send_response(client_ctx, vfs_msg::MSG_VFS_MKDIR_RESPONSE, &response)
     }
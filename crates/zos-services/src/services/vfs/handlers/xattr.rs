@@ -0,0 +1,264 @@
+//! Extended attribute handlers for VFS Service
+//!
+//! Handles: setxattr, getxattr, listxattr
+//!
+//! Attributes are kept in the inode record (`Inode::xattrs`), so they move
+//! with a rename, survive content writes and are deleted with the entry.
+//! Reading them needs read permission on the entry, changing them write
+//! permission. A set reads the inode, changes it and writes it back; the
+//! entry's modification time is left alone and watchers are not notified.
+//!
+//! Like hard links and watches, attributes are a storage feature: requests
+//! for paths under a memory or asset mount fail with `NotSupported`.
+//!
+//! # Safety Properties
+//!
+//! - **Success**: inode written back with the attribute changed
+//! - **Acceptable partial failure**: a set racing another change to the same
+//!   inode may be overwritten by it
+//! - **Forbidden**: Changing attributes without write permission, reading
+//!   them without read permission
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use zos_apps::syscall;
+use zos_apps::{AppError, Message};
+use zos_ipc::storage::ResultKind;
+use zos_vfs::core::validate_xattr_name;
+use zos_vfs::ipc::{
+    vfs_msg, GetXattrRequest, GetXattrResponse, ListXattrRequest, ListXattrResponse,
+    SetXattrRequest, SetXattrResponse,
+};
+use zos_vfs::service::{check_read, check_write, PermissionContext};
+use zos_vfs::VfsError;
+
+use super::super::{inode_key, parse_inode, validate_path, ClientContext, PendingOp, VfsService};
+use super::storage_failure;
+
+/// What an extended attribute request asks for.
+#[derive(Clone, Debug)]
+pub enum XattrRequest {
+    /// Set an attribute, or remove it if `value` is `None`
+    Set { name: String, value: Option<String> },
+    /// Get an attribute's value
+    Get { name: String },
+    /// List attribute names
+    List,
+}
+
+impl XattrRequest {
+    /// Tag the request is answered with.
+    pub fn response_tag(&self) -> u32 {
+        match self {
+            XattrRequest::Set { .. } => vfs_msg::MSG_VFS_SETXATTR_RESPONSE,
+            XattrRequest::Get { .. } => vfs_msg::MSG_VFS_GETXATTR_RESPONSE,
+            XattrRequest::List => vfs_msg::MSG_VFS_LISTXATTR_RESPONSE,
+        }
+    }
+
+    /// Attribute name the request names, if any.
+    fn name(&self) -> Option<&str> {
+        match self {
+            XattrRequest::Set { name, .. } | XattrRequest::Get { name } => Some(name),
+            XattrRequest::List => None,
+        }
+    }
+}
+
+/// Stages for the extended attribute state machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XattrStage {
+    /// Reading the inode and checking permissions
+    ReadingInode,
+    /// Writing the changed inode back (set only)
+    WritingInode,
+}
+
+impl VfsService {
+    /// Send the error response for an extended attribute request.
+    fn send_xattr_error(
+        &self,
+        client_ctx: &ClientContext,
+        request: &XattrRequest,
+        error: VfsError,
+    ) -> Result<(), AppError> {
+        let tag = request.response_tag();
+        match request {
+            XattrRequest::Set { .. } => {
+                self.send_response(client_ctx, tag, &SetXattrResponse { result: Err(error) })
+            }
+            XattrRequest::Get { .. } => {
+                self.send_response(client_ctx, tag, &GetXattrResponse { result: Err(error) })
+            }
+            XattrRequest::List => {
+                self.send_response(client_ctx, tag, &ListXattrResponse { result: Err(error) })
+            }
+        }
+    }
+
+    /// Handle MSG_VFS_SETXATTR - set or remove an extended attribute
+    pub fn handle_setxattr(&mut self, msg: &Message) -> Result<(), AppError> {
+        match serde_json::from_slice::<SetXattrRequest>(&msg.data) {
+            Ok(r) => {
+                let request = XattrRequest::Set {
+                    name: r.name,
+                    value: r.value,
+                };
+                self.start_xattr(msg, r.path, request)
+            }
+            Err(e) => self.refuse_xattr(msg, e),
+        }
+    }
+
+    /// Handle MSG_VFS_GETXATTR - get an extended attribute's value
+    pub fn handle_getxattr(&mut self, msg: &Message) -> Result<(), AppError> {
+        match serde_json::from_slice::<GetXattrRequest>(&msg.data) {
+            Ok(r) => self.start_xattr(msg, r.path, XattrRequest::Get { name: r.name }),
+            Err(e) => self.refuse_xattr(msg, e),
+        }
+    }
+
+    /// Handle MSG_VFS_LISTXATTR - list extended attribute names
+    pub fn handle_listxattr(&mut self, msg: &Message) -> Result<(), AppError> {
+        match serde_json::from_slice::<ListXattrRequest>(&msg.data) {
+            Ok(r) => self.start_xattr(msg, r.path, XattrRequest::List),
+            Err(e) => self.refuse_xattr(msg, e),
+        }
+    }
+
+    /// Answer a request that didn't parse.
+    fn refuse_xattr(&self, msg: &Message, e: serde_json::Error) -> Result<(), AppError> {
+        let request = match msg.tag {
+            vfs_msg::MSG_VFS_SETXATTR => XattrRequest::Set {
+                name: String::new(),
+                value: None,
+            },
+            vfs_msg::MSG_VFS_GETXATTR => XattrRequest::Get {
+                name: String::new(),
+            },
+            _ => XattrRequest::List,
+        };
+        let error = VfsError::InvalidRequest(format!("Failed to parse request: {}", e));
+        self.send_xattr_error(&self.client_context(msg), &request, error)
+    }
+
+    /// Check the request, then read the inode it names.
+    fn start_xattr(
+        &mut self,
+        msg: &Message,
+        path: String,
+        request: XattrRequest,
+    ) -> Result<(), AppError> {
+        let client_ctx = self.client_context(msg);
+        let invalid = match validate_path(&path) {
+            Err(reason) => Some(VfsError::InvalidPath(String::from(reason))),
+            Ok(()) => request.name().and_then(|name| validate_xattr_name(name).err()),
+        };
+        if let Some(error) = invalid {
+            return self.send_xattr_error(&client_ctx, &request, error);
+        }
+
+        syscall::debug(&format!("VfsService: {:?} on {}", request, path));
+
+        let perm_ctx = self.permission_context(msg.from_pid, &path);
+        self.start_storage_read(
+            &inode_key(&path),
+            PendingOp::XattrOp {
+                ctx: client_ctx,
+                path,
+                perm_ctx,
+                request,
+                stage: XattrStage::ReadingInode,
+            },
+        )
+    }
+
+    /// Handle extended attribute operation result - dispatches based on stage
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle_xattr_op_result(
+        &mut self,
+        client_ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        request: XattrRequest,
+        stage: XattrStage,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        if stage == XattrStage::WritingInode {
+            let result = match result_type {
+                ResultKind::WriteOk => Ok(()),
+                _ => Err(storage_failure("Inode write", result_type)),
+            };
+            let response = SetXattrResponse { result };
+            return self.send_response(&client_ctx, vfs_msg::MSG_VFS_SETXATTR_RESPONSE, &response);
+        }
+
+        let mut inode = match result_type {
+            ResultKind::ReadOk => match parse_inode(data) {
+                Ok(inode) => inode,
+                Err(e) => {
+                    let error = VfsError::StorageError(format!("Failed to parse inode: {}", e));
+                    return self.send_xattr_error(&client_ctx, &request, error);
+                }
+            },
+            ResultKind::NotFound => {
+                return self.send_xattr_error(&client_ctx, &request, VfsError::NotFound)
+            }
+            _ => {
+                let error = storage_failure("Inode read", result_type);
+                return self.send_xattr_error(&client_ctx, &request, error);
+            }
+        };
+
+        let allowed = match request {
+            XattrRequest::Set { .. } => check_write(&inode, &perm_ctx),
+            XattrRequest::Get { .. } | XattrRequest::List => check_read(&inode, &perm_ctx),
+        };
+        if !allowed {
+            syscall::debug(&format!(
+                "VfsService: Permission denied for {:?} on {} (pid={})",
+                request, path, client_ctx.pid
+            ));
+            return self.send_xattr_error(&client_ctx, &request, VfsError::PermissionDenied);
+        }
+
+        let (name, value) = match &request {
+            XattrRequest::Get { name } => {
+                let response = GetXattrResponse {
+                    result: Ok(inode.xattrs.get(name).cloned()),
+                };
+                return self.send_response(&client_ctx, request.response_tag(), &response);
+            }
+            XattrRequest::List => {
+                let names: Vec<String> = inode.xattrs.keys().cloned().collect();
+                let response = ListXattrResponse { result: Ok(names) };
+                return self.send_response(&client_ctx, request.response_tag(), &response);
+            }
+            XattrRequest::Set { name, value } => (name, value),
+        };
+
+        if let Err(error) = inode.set_xattr(name, value.clone()) {
+            return self.send_xattr_error(&client_ctx, &request, error);
+        }
+        let inode_json = match serde_json::to_vec(&inode) {
+            Ok(j) => j,
+            Err(e) => {
+                let error = VfsError::StorageError(format!("Failed to serialize inode: {}", e));
+                return self.send_xattr_error(&client_ctx, &request, error);
+            }
+        };
+        self.start_storage_write(
+            &inode_key(&path),
+            &inode_json,
+            PendingOp::XattrOp {
+                ctx: client_ctx,
+                path,
+                perm_ctx,
+                request,
+                stage: XattrStage::WritingInode,
+            },
+        )
+    }
+}
@@ -44,13 +44,16 @@
 //! │   Supervisor    │  ◄── notify_storage_read_complete()
 //! └────────┬────────┘
 //!          │
-//!          │ IPC (MSG_STORAGE_RESULT)
+//!          │ IPC (MSG_STORAGE_RESULT, or MSG_STORAGE_RESULT_BATCH)
 //!          ▼
 //! ┌─────────────────┐
 //! │   VFS Service   │  ◄── Matches request_id, sends response to client
 //! └─────────────────┘
 //! ```
 //!
+//! Results that complete close together (e.g. during a directory scan) reach
+//! VFS as one MSG_STORAGE_RESULT_BATCH, handled entry by entry.
+//!
 //! # Protocol
 //!
 //! Processes communicate with VfsService via IPC:
@@ -61,8 +64,43 @@
 //! - `MSG_VFS_WRITE (0x8010)`: Write file
 //! - `MSG_VFS_READ (0x8012)`: Read file
 //! - `MSG_VFS_UNLINK (0x8014)`: Delete file
+//! - `MSG_VFS_RENAME (0x8016)`: Rename/move a file or directory
+//! - `MSG_VFS_LINK (0x801A)`: Give a file a second name (hard link)
 //! - `MSG_VFS_STAT (0x8020)`: Get file/directory info
 //! - `MSG_VFS_EXISTS (0x8022)`: Check if path exists
+//! - `MSG_VFS_QUOTA_STAT (0x8034)`: Get a user's storage quota and usage
+//! - `MSG_VFS_SIGNED_REQUEST (0x8040)`: Privileged rmdir/unlink signed by a service
+//! - `MSG_VFS_REGISTER_SERVICE_KEY (0x8042)`: Pin a service's signing key
+//! - `MSG_VFS_OPEN (0x8050)`: Open a file handle
+//! - `MSG_VFS_READ_AT (0x8052)`: Read a range through a handle
+//! - `MSG_VFS_WRITE_AT (0x8054)`: Write a range through a handle
+//! - `MSG_VFS_CLOSE (0x8056)`: Close a file handle
+//! - `MSG_VFS_ATTACH_SHM (0x8058)`: Attach a shared memory region to a handle
+//! - `MSG_VFS_WATCH (0x8060)`: Watch a path for changes
+//! - `MSG_VFS_UNWATCH (0x8062)`: Remove a watch
+//! - `MSG_VFS_MOUNT (0x8070)`: Mount a backend at a path prefix, or remount a
+//!   memory mount with a new size limit (Init/PM only)
+//! - `MSG_VFS_UMOUNT (0x8072)`: Remove a mount (Init/PM only)
+//! - `MSG_VFS_MOUNTS (0x8074)`: List the mount table
+//! - `MSG_VFS_SET_CALLER_USER (0x8080)`: Bind a process or the session to a
+//!   user (Init/PM/Identity only)
+//! - `MSG_VFS_BATCH (0x8090)`: Run several operations, answered with one
+//!   response holding each operation's own (see `handlers::batch`)
+//! - `MSG_VFS_SETXATTR (0x80A0)`: Set or remove an extended attribute
+//! - `MSG_VFS_GETXATTR (0x80A2)`: Get an extended attribute
+//! - `MSG_VFS_LISTXATTR (0x80A4)`: List extended attribute names
+//! - `MSG_VFS_TRASH_LIST (0x80B0)`: List a user's trashed files
+//! - `MSG_VFS_TRASH_RESTORE (0x80B2)`: Move a trashed file back
+//! - `MSG_VFS_TRASH_PURGE (0x80B4)`: Delete trashed files for good
+//! - `MSG_VFS_SNAPSHOT_CREATE (0x80C0)`: Snapshot a directory tree
+//! - `MSG_VFS_SNAPSHOT_LIST (0x80C2)`: List a user's snapshots
+//! - `MSG_VFS_SNAPSHOT_RESTORE (0x80C4)`: Put a tree back as a snapshot saw it
+//! - `MSG_VFS_SNAPSHOT_DELETE (0x80C6)`: Delete a snapshot
+//! - `MSG_VFS_HASH (0x80D0)`: SHA-256 of a file, computed here
+//!
+//! Watchers are sent `MSG_VFS_EVENT (0x8064)` after each committed create,
+//! write, delete or rename of a path their watch covers.
+//! - `MSG_CANCEL_REQUEST (0x0010)`: Cancel the sender's in-flight requests
 //!
 //! # Note on Key Storage
 //!
@@ -70,12 +108,74 @@
 //! KeystoreService (PID 7), not VFS. This provides security isolation
 //! by storing keys in a separate zos-keystore IndexedDB database.
 //!
+//! VFS is itself a keystore client: the content of files owned by a user is
+//! stored encrypted under that user's content key, which VFS loads from the
+//! keystore (see `handlers::encryption`). Keystore responses are handled in
+//! `on_message` alongside client requests.
+//!
 //! # Permission Model
 //!
 //! The VFS service enforces permissions based on caller context:
-//! - System processes (PID 1-9) have full access
-//! - User applications check owner/world permissions on inodes
-//! - User ID is extracted from path (e.g., `/users/{user_id}/...`)
+//! - Init (PID 1) is the superuser and bypasses every check
+//! - System processes (PID 2-9) have full access; the user ID for
+//!   ownership is extracted from path (e.g., `/users/{user_id}/...`)
+//! - User applications check owner/world permissions on inodes as the
+//!   session user (see `handlers::callers`)
+//!
+//! Files count against their owner's storage quota; a write that would take
+//! the owner past it fails with `VfsError::UserQuotaExceeded`.
+//!
+//! # Large Files
+//!
+//! Content larger than one chunk is stored as fixed-size chunk records named
+//! by a manifest under the file's content key (see `handlers::chunks`).
+//! `MSG_VFS_READ` and `MSG_VFS_WRITE` still move whole files of up to
+//! `MAX_CONTENT_SIZE`; handles reach files up to `MAX_CHUNKED_SIZE`,
+//! touching only the chunks each call covers.
+//!
+//! # Hard Links
+//!
+//! Linked files share one content-addressed blob, counted by reference;
+//! their content keys hold link records naming it (see `handlers::link`).
+//! Reads follow the link, writes give the file a record of its own, and
+//! the blob is deleted with its last reference.
+//!
+//! # Extended Attributes
+//!
+//! Files and directories carry small named values (MIME type, tags, origin
+//! URL) in their inode record, kept across content writes and renames (see
+//! `handlers::xattr`).
+//!
+//! # Trash
+//!
+//! `MSG_VFS_UNLINK` with `trash` set moves the file to
+//! `/home/{user}/.trash` instead of deleting it, with its original path kept
+//! in extended attributes; trashed files still count against their owner's
+//! quota. Entries are purged on request or once they are old enough (see
+//! `handlers::trash`).
+//!
+//! # Snapshots
+//!
+//! A snapshot copies the inodes of a tree in a user's home and shares each
+//! file's content the way a hard link does, so nothing is copied until the
+//! live file is written. Requests that would change a tree are refused with
+//! `Retry` while it is being snapshotted or restored (see
+//! `handlers::snapshot`).
+//!
+//! # Integrity
+//!
+//! Whole-file writes record the SHA-256 of the content in the inode.
+//! `MSG_VFS_HASH` hashes a file chunk by chunk as it streams it from
+//! storage, so clients can check files of any size without reading them,
+//! and fails if the content no longer matches the recorded hash. With the
+//! `vfs.verify_hashes` flag set, whole-file reads are checked the same way
+//! (see `handlers::hash`).
+//!
+//! # Mounts
+//!
+//! Storage serves `/`. Other path prefixes can be mounted on an in-memory
+//! filesystem or a read-only set of bundled assets, which are served
+//! in-process, or on storage marked read-only (see `handlers::mount`).
 
 extern crate alloc;
 
@@ -84,16 +184,47 @@ pub mod handlers;
 #[cfg(test)]
 mod tests;
 
+use alloc::boxed::Box;
+use core::cell::RefCell;
 use alloc::collections::BTreeMap;
 use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
 use crate::manifests::VFS_MANIFEST;
+use crate::signing::TrustedServiceKeys;
+use crate::trust::{CallerClass, CallerNames};
 use zos_apps::syscall;
-use zos_apps::{AppContext, AppError, AppManifest, ControlFlow, Message, ZeroApp};
-use zos_process::MSG_STORAGE_RESULT;
-use zos_vfs::ipc::vfs_msg;
+use zos_apps::framework::DEFAULT_WATCHDOG_PERIOD_MS;
+use zos_apps::{
+    AppContext, AppError, AppManifest, ControlFlow, Drain, Message, StateStore, StatefulService,
+    Watchdog, ZeroApp,
+};
+use zos_process::{ResultKind, StorageResult, MSG_STORAGE_RESULT, MSG_STORAGE_RESULT_BATCH};
+use zos_vfs::client::keystore_async;
+use zos_vfs::ipc::{vfs_msg, RmdirFailure, VfsStats};
+use zos_vfs::schema::decode_inode;
 use zos_vfs::service::{PermissionContext, ProcessClass};
+use zos_vfs::storage::blobs::{is_link, BlobHash};
+use zos_vfs::storage::chunking::ChunkManifest;
+use zos_vfs::storage::integrity::ContentHash;
+use zos_vfs::{Inode, UserId, VfsError, Xattrs};
+
+use handlers::batch::{BatchSlot, BatchTable};
+use handlers::callers::CallerUsers;
+use handlers::checkpoint::VfsState;
+use handlers::chunks::{ChunkPatch, ChunkedRead};
+use handlers::encryption::ContentKeys;
+use handlers::gc::{GcStage, GcSweep};
+use handlers::hash::HashStage;
+use handlers::handles::HandleTable;
+use handlers::link::HeldContent;
+use handlers::watch::WatchTable;
+use handlers::xattr::{XattrRequest, XattrStage};
+use handlers::migrate::MigrationSweep;
+use handlers::trash::{TrashRequest, TrashScan, TrashStage, TrashSweep};
+use handlers::snapshot::{SnapshotJob, SnapshotStage};
+use handlers::mount::MountSet;
+use handlers::quota::{Charge, QuotaTable, Reservation};
 
 // =============================================================================
 // Resource Limits (Rule 11)
@@ -105,12 +236,25 @@ use zos_vfs::service::{PermissionContext, ProcessClass};
 /// If exceeded, new operations return ResourceExhausted error.
 pub const MAX_PENDING_OPS: usize = 1024;
 
+/// How long a storage operation may wait for its result (2 minutes).
+///
+/// Past this the operation is dropped and its client answered with a
+/// timeout error, whether or not the request carried a deadline. The
+/// watchdog normally aborts a stuck operation well before.
+pub const OP_TIMEOUT_NS: u64 = 120_000_000_000;
+
 /// Maximum content size for file writes (16 MB).
 ///
 /// This prevents resource exhaustion from very large write requests.
 /// If exceeded, write operations return ContentTooLarge error.
 pub const MAX_CONTENT_SIZE: usize = 16 * 1024 * 1024;
 
+/// Maximum size of a file grown through a handle.
+///
+/// Write-at only holds the chunks it touches, so this is bounded by
+/// storage rather than by the heap.
+pub use zos_vfs::storage::chunking::MAX_CHUNKED_SIZE;
+
 // =============================================================================
 // Storage Key Helpers
 // =============================================================================
@@ -127,20 +271,18 @@ pub fn content_key(path: &str) -> String {
     format!("content:{}", path)
 }
 
-/// Format a storage result type as a human-readable string.
-pub fn result_type_name(result_type: u8) -> &'static str {
-    use zos_process::storage_result;
-    match result_type {
-        storage_result::READ_OK => "READ_OK",
-        storage_result::WRITE_OK => "WRITE_OK",
-        storage_result::NOT_FOUND => "NOT_FOUND",
-        storage_result::ERROR => "ERROR",
-        storage_result::LIST_OK => "LIST_OK",
-        storage_result::EXISTS_OK => "EXISTS_OK",
-        _ => "UNKNOWN",
-    }
+/// Decode a stored inode, upgrading records from older schema versions.
+///
+/// The upgrade is in memory only; the migration sweep persists it.
+pub fn parse_inode(data: &[u8]) -> Result<Inode, String> {
+    decode_inode(data)
+        .map(|upgraded| upgraded.record)
+        .map_err(|e| e.message())
 }
 
+/// Split a MSG_STORAGE_RESULT_BATCH payload into its result payloads.
+pub use zos_process::storage::split_result_batch as split_storage_result_batch;
+
 // =============================================================================
 // Pending Storage Operations
 // =============================================================================
@@ -150,12 +292,18 @@ pub fn result_type_name(result_type: u8) -> &'static str {
 /// Captures information needed to send responses:
 /// - `pid`: The client process ID
 /// - `reply_caps`: Capability slots for direct IPC reply (transferred from request)
+/// - `batch`: Where the response goes if the request is part of a batch
+/// - `correlation_id`: Echoed in the response
 #[derive(Clone, Debug)]
 pub struct ClientContext {
     /// Client process ID
     pub pid: u32,
     /// Reply capability slots (for direct IPC response)
     pub reply_caps: Vec<u32>,
+    /// Batch slot the response is recorded in, instead of being sent
+    pub batch: Option<BatchSlot>,
+    /// Correlation ID the request carried
+    pub correlation_id: Option<u32>,
 }
 
 impl ClientContext {
@@ -164,6 +312,8 @@ impl ClientContext {
         Self {
             pid: msg.from_pid,
             reply_caps: msg.cap_slots.clone(),
+            batch: None,
+            correlation_id: msg.correlation_id,
         }
     }
 }
@@ -185,6 +335,8 @@ pub enum PendingOp {
         path: String,
         /// Permission context for access control
         perm_ctx: PermissionContext,
+        /// Hash the content must match, if reads are verified
+        expected_hash: Option<ContentHash>,
     },
     /// Put inode (after put, send response if ctx is Some)
     ///
@@ -238,14 +390,81 @@ pub enum PendingOp {
     ///
     /// Stages:
     /// 1. Check parent exists and is directory, check permissions
-    /// 2. Write content first
-    /// 3. Write inode (only after content succeeds)
-    /// 4. Send response (only after inode succeeds)
+    /// 2. Read the inode being replaced and reserve the owner's quota
+    /// 3. Write content first
+    /// 4. Write inode (only after content succeeds)
+    /// 5. Send response (only after inode succeeds)
     WriteFileOp {
         ctx: ClientContext,
         path: String,
         perm_ctx: PermissionContext,
         stage: WriteFileStage,
+        /// Which request the write answers
+        reply: WriteReply,
+    },
+    /// Open a file handle: read the inode to check type and permissions
+    ///
+    /// With `create` set, a missing file is created empty through
+    /// `WriteFileOp` before the handle is handed out.
+    OpenFileOp {
+        ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        writable: bool,
+        create: bool,
+    },
+    /// Watch a path: read the inode to check it exists and is readable
+    WatchOp {
+        ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        recursive: bool,
+    },
+    /// Read through a handle: fetch the content and return a slice of it,
+    /// or place it in the shared memory of handle `shm`
+    ReadAtOp {
+        ctx: ClientContext,
+        offset: u64,
+        length: u64,
+        shm: Option<u32>,
+    },
+    /// Hash a file
+    ///
+    /// Stages:
+    /// 1. Read the inode to check type and permissions
+    /// 2. Read the content record, then each chunk it names (as a
+    ///    `ChunkReadOp`) if it is a manifest
+    HashOp {
+        ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        stage: HashStage,
+    },
+    /// Read a chunked file: fetch the chunks the read covers, one at a time
+    ChunkReadOp {
+        ctx: ClientContext,
+        read: ChunkedRead,
+    },
+    /// Write through a handle into a chunked file
+    ///
+    /// Stages:
+    /// 1. Read the inode and reserve the owner's quota
+    /// 2. Rewrite each chunk the data touches, reading it first if some of
+    ///    its bytes stay
+    /// 3. Write the manifest (if the file grew), then the inode
+    ChunkPatchOp {
+        ctx: ClientContext,
+        patch: ChunkPatch,
+        stage: PatchStage,
+    },
+    /// Write through a handle: fetch the content, splice the data in and
+    /// hand the result to `WriteFileOp`
+    WriteAtOp {
+        ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        offset: u64,
+        data: Vec<u8>,
     },
     /// Mkdir operation - tracks the state machine for directory creation
     ///
@@ -290,6 +509,147 @@ pub enum PendingOp {
         perm_ctx: PermissionContext,
         stage: UnlinkStage,
     },
+    /// Rename operation - tracks the state machine for moving an entry
+    ///
+    /// Stages:
+    /// 1. Read source inode and check permissions
+    /// 2. Check the target is free and its parent is a writable directory
+    /// 3. Write content (files) and inode under the new path
+    /// 4. Directories only: copy every descendant under the new path
+    ///    (see [`MoveWalk`]), then delete the old ones
+    /// 5. Delete the old inode, then the old content
+    RenameOp {
+        ctx: ClientContext,
+        from: String,
+        to: String,
+        perm_ctx: PermissionContext,
+        stage: RenameStage,
+        /// Which request the move answers
+        reply: RenameReply,
+    },
+    /// Link operation - tracks the state machine for a hard link
+    ///
+    /// Stages:
+    /// 1. Read the source inode and check permissions
+    /// 2. Check the link path is free and its parent a writable directory
+    /// 3. Read the source content and the count of the blob it names or
+    ///    becomes
+    /// 4. Write the blob, count, link records and new inode in one batch
+    LinkOp {
+        ctx: ClientContext,
+        path: String,
+        link_path: String,
+        perm_ctx: PermissionContext,
+        stage: LinkStage,
+    },
+    /// Rmdir operation - tracks the state machine for directory removal
+    ///
+    /// Stages:
+    /// 1. List the directory's children (must be none unless recursive)
+    /// 2. Recursive only: read each descendant's inode and check permissions,
+    ///    listing subdirectories as they are reached
+    /// 3. Delete entries bottom-up (content before inode for files), ending
+    ///    with the directory itself
+    RmdirOp {
+        ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        recursive: bool,
+        walk: RmdirWalk,
+        stage: RmdirStage,
+    },
+    /// Extended attribute operation
+    ///
+    /// Stages:
+    /// 1. Read the inode and check permissions; get and list answer here
+    /// 2. Set only: write the changed inode back
+    XattrOp {
+        ctx: ClientContext,
+        path: String,
+        perm_ctx: PermissionContext,
+        request: XattrRequest,
+        stage: XattrStage,
+    },
+    /// Trash list or purge, from a client or the background sweep
+    ///
+    /// Stages:
+    /// 1. Read the trash directory and check permissions
+    /// 2. List it (unless purging one entry)
+    /// 3. Read each entry's inode; purged files have their content, then
+    ///    their inode deleted
+    TrashOp {
+        /// `None` for the background sweep
+        ctx: Option<ClientContext>,
+        user_id: UserId,
+        perm_ctx: PermissionContext,
+        request: TrashRequest,
+        scan: TrashScan,
+        stage: TrashStage,
+    },
+    /// Trash sweep: list the home directories to find every user's trash
+    TrashSweepHomes,
+    /// Snapshot create, list, restore or delete
+    ///
+    /// Stages:
+    /// 1. Read the user's snapshot index (and the manifest, unless creating)
+    /// 2. Create: walk the tree, taking a blob reference to each file's
+    ///    content, then write the manifest and index together
+    /// 3. Restore: walk the live tree removing what the snapshot lacks, then
+    ///    write each entry back top-down
+    /// 4. Delete: write the index, drop the blob references, then delete the
+    ///    manifest
+    SnapshotOp {
+        ctx: ClientContext,
+        job: Box<SnapshotJob>,
+        stage: SnapshotStage,
+    },
+    /// Migration sweep: read an inode to upgrade it
+    MigrateInode { path: String },
+    /// Migration sweep: list a directory's children
+    MigrateList { path: String },
+    /// Migration sweep: write back an upgraded inode
+    MigrateWrite { path: String },
+    /// Migration sweep: seal an owned file stored as plaintext
+    ///
+    /// Stages:
+    /// 1. Read the content
+    /// 2. Write it back sealed under the owner's key
+    /// 3. Write the inode back marked encrypted
+    MigrateSeal {
+        path: String,
+        /// The inode as the sweep read it
+        inode: Inode,
+        stage: SealStage,
+    },
+    /// Delete a file's content record, reading it first to find the chunks
+    /// or blob it holds; the result of the delete is then handed to `then`
+    ContentDeleteOp {
+        path: String,
+        stage: ContentDeleteStage,
+        then: Box<PendingOp>,
+    },
+    /// Delete chunks no content record names any more, one at a time
+    ReleaseChunks {
+        manifest: ChunkManifest,
+        next: u32,
+    },
+    /// Read the blob a link record names, then hand it to `then` in place
+    /// of the record
+    FollowLink { then: Box<PendingOp> },
+    /// Drop one reference to a blob, deleting it with the last
+    ReleaseBlob {
+        hash: BlobHash,
+        stage: ReleaseStage,
+    },
+    /// Garbage collection: a step of finding and deleting an orphaned
+    /// content record or blob
+    GcOp { stage: GcStage },
+    /// Read the service checkpoint at startup
+    RestoreState,
+    /// Read the feature flag store at startup, or after it is rewritten
+    LoadFlags,
+    /// Write the service checkpoint
+    CheckpointState,
 }
 
 /// Stages for the WriteFile operation state machine.
@@ -302,15 +662,153 @@ pub enum WriteFileStage {
         /// The content to write
         content: Vec<u8>,
     },
+    /// Reading the inode being replaced, to charge the owner's quota
+    ReadingExisting {
+        /// The content to write
+        content: Vec<u8>,
+    },
+    /// Reading the content record being replaced, to find the chunks or
+    /// blob it holds
+    ReadingReplaced {
+        /// The content to write
+        content: Vec<u8>,
+        /// Charge of the inode being replaced
+        released: Option<Charge>,
+        /// Extended attributes of the file being replaced, kept
+        xattrs: Xattrs,
+    },
+    /// Storing chunk `next` of content too large for one record
+    WritingChunks {
+        /// The content to write
+        content: Vec<u8>,
+        /// Manifest naming the new chunks
+        manifest: ChunkManifest,
+        next: u32,
+        /// Quota change to undo if the write fails
+        reservation: Reservation,
+        /// What the content being replaced holds
+        replaced: Option<HeldContent>,
+        /// Extended attributes for the new inode
+        xattrs: Xattrs,
+    },
     /// Writing content (stage 1 of 2)
     WritingContent {
         /// Size for inode metadata
         content_len: u64,
+        /// Whether the content was stored sealed
+        encrypted: bool,
+        /// Hash of the content, recorded in the new inode
+        content_hash: ContentHash,
+        /// Quota change to undo if the write fails
+        reservation: Reservation,
+        /// Chunks the new content record names, released if it fails
+        chunks: Option<ChunkManifest>,
+        /// What the content being replaced holds, released once it lands
+        replaced: Option<HeldContent>,
+        /// Extended attributes for the new inode
+        xattrs: Xattrs,
     },
     /// Writing inode metadata (stage 2 of 2)
+    WritingInode {
+        /// Quota change to undo if the write fails
+        reservation: Reservation,
+    },
+}
+
+/// Stages for sealing a plaintext file during the migration sweep.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SealStage {
+    ReadingContent,
+    WritingContent,
     WritingInode,
 }
 
+/// Stages for writing through a handle into a chunked file.
+#[derive(Clone)]
+pub enum PatchStage {
+    /// Reading the inode, to charge the owner's quota
+    ReadingInode,
+    /// Reading chunk `index`, to keep the bytes the write doesn't cover
+    ReadingChunk {
+        index: u32,
+        inode: Box<Inode>,
+        reservation: Reservation,
+    },
+    /// Writing chunk `index` back
+    WritingChunk {
+        index: u32,
+        inode: Box<Inode>,
+        reservation: Reservation,
+    },
+    /// Writing the manifest for the new size
+    WritingManifest {
+        inode: Box<Inode>,
+        reservation: Reservation,
+    },
+    /// Writing the inode for the new size
+    WritingInode { size: u64, reservation: Reservation },
+}
+
+/// Stages for deleting a content record.
+#[derive(Clone)]
+pub enum ContentDeleteStage {
+    /// Reading the record to see what it holds
+    ReadingRecord,
+    /// Deleting the record; what it held is released once it is gone
+    DeletingRecord { held: Option<HeldContent> },
+}
+
+/// Response a `WriteFileOp` sends once it finishes.
+///
+/// Plain writes, opens that create their file and writes through a handle
+/// all commit through the same state machine but answer different requests.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WriteReply {
+    /// `MSG_VFS_WRITE`
+    Write,
+    /// `MSG_VFS_OPEN` with `create`: hand out a handle once the file exists
+    Open { writable: bool },
+    /// `MSG_VFS_WRITE_AT`: report the new file size
+    WriteAt { size: u64 },
+}
+
+impl WriteReply {
+    /// Tag of the response this write ends with.
+    pub fn response_tag(&self) -> u32 {
+        match self {
+            WriteReply::Write => vfs_msg::MSG_VFS_WRITE_RESPONSE,
+            WriteReply::Open { .. } => vfs_msg::MSG_VFS_OPEN_RESPONSE,
+            WriteReply::WriteAt { .. } => vfs_msg::MSG_VFS_WRITE_AT_RESPONSE,
+        }
+    }
+}
+
+/// Response a `RenameOp` sends once it finishes.
+///
+/// Moving a file to the trash and back out of it commit through the rename
+/// state machine but answer different requests.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenameReply {
+    /// `MSG_VFS_RENAME`
+    Rename,
+    /// `MSG_VFS_UNLINK` with `trash`: mark the moved inode as trashed
+    Trash { deleted_at: u64 },
+    /// `MSG_VFS_TRASH_RESTORE`: clear the marks; an empty target is taken
+    /// from them
+    Restore,
+}
+
+impl RenameReply {
+    /// Tag of the response this move ends with.
+    pub fn response_tag(&self) -> u32 {
+        match self {
+            RenameReply::Rename => vfs_msg::MSG_VFS_RENAME_RESPONSE,
+            RenameReply::Trash { .. } => vfs_msg::MSG_VFS_UNLINK_RESPONSE,
+            RenameReply::Restore => vfs_msg::MSG_VFS_TRASH_RESTORE_RESPONSE,
+        }
+    }
+}
+
 /// Stages for the Mkdir operation state machine.
 ///
 /// This ensures parent permissions are checked before creating the directory.
@@ -353,9 +851,206 @@ pub enum UnlinkStage {
     /// Reading inode to verify it's a file and check permissions
     ReadingInode,
     /// Deleting content (must complete before inode delete)
-    DeletingContent,
-    /// Deleting inode (final step)
-    DeletingInode,
+    DeletingContent { charge: Option<Charge> },
+    /// Deleting inode (final step); the charge is released once it's gone
+    DeletingInode { charge: Option<Charge> },
+}
+
+/// Stages for the Rename operation state machine.
+///
+/// The entry is committed under the new path before anything under the old
+/// path is removed, so a failure never loses it.
+#[derive(Clone)]
+pub enum RenameStage {
+    /// Reading source inode to check permissions
+    ReadingSource,
+    /// Checking nothing exists at the target path
+    CheckingTarget { inode: Box<Inode> },
+    /// Checking target parent is a directory we can write to
+    CheckingParent { inode: Box<Inode> },
+    /// Creating the trash directory a file is being moved to
+    CreatingTrashDir { inode: Box<Inode> },
+    /// Reading file content to move
+    ReadingContent { inode: Box<Inode> },
+    /// Writing content under the new path
+    WritingContent { inode: Box<Inode> },
+    /// Writing inode under the new path
+    WritingInode { is_file: bool },
+    /// Deleting the old inode
+    DeletingSourceInode { is_file: bool },
+    /// Deleting the old content (failure only orphans it)
+    DeletingSourceContent,
+    /// Moving a directory's descendants, once its own inode is written
+    MovingSubtree { walk: MoveWalk, step: SubtreeStage },
+}
+
+/// Descendants of a directory being moved.
+///
+/// Children are stored under keys derived from their directory's path, so
+/// each one is copied to the keys of its new path, top-down and content
+/// before inode. Only once every copy is written are the old entries
+/// removed, deepest first. If a copy fails, the copies made so far are
+/// removed again and the old tree is left as it was.
+#[derive(Clone, Default)]
+pub struct MoveWalk {
+    /// Steps still to run, taken from the end
+    pub steps: Vec<MoveStep>,
+    /// Entries copied so far (by old path), in the order they were copied
+    pub copied: Vec<MovedEntry>,
+    /// Why the move is being undone
+    pub error: Option<VfsError>,
+}
+
+/// One step of a [`MoveWalk`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoveStep {
+    /// List a directory and copy its children
+    List(String),
+    /// Copy an entry under the new path (then list it, for a directory)
+    Copy(String),
+}
+
+/// An entry copied by a [`MoveWalk`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MovedEntry {
+    /// Path under the old directory
+    pub path: String,
+    pub is_file: bool,
+}
+
+impl MoveWalk {
+    /// Record the failure that undoes the move; later ones are only logged.
+    pub fn fail(&mut self, path: &str, error: VfsError) {
+        syscall::debug(&format!("VfsService: move of {} failed: {:?}", path, error));
+        self.error.get_or_insert(error);
+    }
+}
+
+/// Stages of a [`MoveWalk`].
+#[derive(Clone)]
+pub enum SubtreeStage {
+    /// Listing a directory under the old path
+    Listing { dir: String },
+    /// Reading an entry's inode under the old path
+    ReadingInode { path: String },
+    /// Reading a file's content under the old path
+    ReadingContent { path: String, inode: Box<Inode> },
+    /// Writing a file's content under the new path
+    WritingContent { path: String, inode: Box<Inode> },
+    /// Writing an entry's inode under the new path
+    WritingInode { path: String, is_file: bool },
+    /// Deleting an entry under the old path or, when undoing, the new one:
+    /// its inode, then its content
+    Deleting { entry: MovedEntry, content: bool },
+    /// Undoing: deleting the directory's own inode under the new path
+    DeletingRoot,
+}
+
+/// Stages for the Link operation state machine.
+///
+/// Nothing is written until the last stage, which commits everything in
+/// one batch.
+#[derive(Clone)]
+pub enum LinkStage {
+    /// Reading the source inode to check permissions
+    ReadingSource,
+    /// Checking nothing exists at the link path
+    CheckingTarget { inode: Box<Inode> },
+    /// Checking the link's parent is a directory we can write to
+    CheckingParent { inode: Box<Inode> },
+    /// Reading the source's content record
+    ReadingContent {
+        inode: Box<Inode>,
+        reservation: Reservation,
+    },
+    /// Reading the link count of the blob
+    ReadingRefcount {
+        inode: Box<Inode>,
+        reservation: Reservation,
+        hash: BlobHash,
+        /// The source's own content record, which becomes the blob (`None`
+        /// if the source is already a link)
+        record: Option<Vec<u8>>,
+    },
+    /// Writing blob, count, link records and inode
+    WritingBatch {
+        reservation: Reservation,
+        hash: BlobHash,
+    },
+}
+
+/// Stages for dropping a reference to a blob.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReleaseStage {
+    /// Reading the link count
+    ReadingCount,
+    /// Writing the count less one
+    WritingCount,
+    /// Reading the blob of the last reference, to find any chunks it names
+    ReadingBlob,
+    /// Deleting the count
+    DeletingCount { manifest: Option<ChunkManifest> },
+    /// Deleting the blob; its chunks follow once it is gone
+    DeletingBlob { manifest: Option<ChunkManifest> },
+}
+
+/// Stages for the Rmdir operation state machine.
+#[derive(Clone)]
+pub enum RmdirStage {
+    /// Listing a directory's children
+    ListingChildren { dir: String },
+    /// Reading a descendant's inode
+    ReadingEntry { path: String },
+    /// Deleting a file's content (its inode is kept if this fails)
+    DeletingContent {
+        path: String,
+        charge: Option<Charge>,
+    },
+    /// Deleting an entry's inode
+    DeletingInode {
+        path: String,
+        charge: Option<Charge>,
+    },
+}
+
+/// Work left in an rmdir.
+///
+/// Steps form a stack. A directory's `Remove` step sits below its `List`
+/// step, so it only runs once everything under it has been handled, and it
+/// is skipped if anything under it failed, which keeps the tree connected.
+#[derive(Clone, Debug, Default)]
+pub struct RmdirWalk {
+    /// Steps still to run, taken from the end
+    pub steps: Vec<RmdirStep>,
+    /// Entries that could not be removed
+    pub failed: Vec<RmdirFailure>,
+}
+
+/// One step of an rmdir walk
+#[derive(Clone, Debug, PartialEq)]
+pub enum RmdirStep {
+    /// Read an entry's inode to decide how to remove it
+    Visit(String),
+    /// List a directory and visit its children
+    List(String),
+    /// Remove an entry whose children have been handled (content first
+    /// for files), releasing its quota charge
+    Remove {
+        path: String,
+        is_file: bool,
+        charge: Option<Charge>,
+    },
+}
+
+impl RmdirWalk {
+    /// Record an entry that can't be removed.
+    pub fn fail(&mut self, path: &str, error: VfsError) {
+        syscall::debug(&format!("VfsService: rmdir keeping {}: {:?}", path, error));
+        self.failed.push(RmdirFailure {
+            path: path.into(),
+            error,
+        });
+    }
 }
 
 /// Type of inode operation
@@ -385,12 +1080,95 @@ pub enum InodeOpType {
 // =============================================================================
 
 /// VFS Service - manages filesystem operations
-#[derive(Default)]
 pub struct VfsService {
     /// Whether we have registered with init
     registered: bool,
     /// Pending storage operations: request_id -> operation context
     pending_ops: BTreeMap<u32, PendingOp>,
+    /// Deadlines of the client requests behind pending operations
+    op_deadlines: BTreeMap<u32, u64>,
+    /// Uptime by which each pending operation's result is due
+    op_timeouts: BTreeMap<u32, u64>,
+    /// Deadline of the request being handled, if the client set one
+    request_deadline: Option<u64>,
+    /// Background inode schema migration
+    migration: MigrationSweep,
+    /// State checkpointed across restarts
+    checkpoint: StatefulService<VfsState>,
+    /// Drain before restart
+    drain: Drain,
+    /// Signing keys pinned by services that send signed requests
+    service_keys: TrustedServiceKeys,
+    /// Names callers run under, for trusting services by name
+    names: CallerNames,
+    /// Open file handles, per client process
+    handles: HandleTable,
+    /// Path watches, per client process
+    watches: WatchTable,
+    /// Storage usage per user
+    quotas: QuotaTable,
+    /// Content keys per user, and the results waiting on them
+    content_keys: ContentKeys,
+    /// Blob references to drop once nothing else is changing their counts
+    blob_releases: Vec<BlobHash>,
+    /// Mount table and the in-process filesystems it serves
+    mounts: MountSet,
+    /// The user each application process acts as
+    callers: CallerUsers,
+    /// Batches awaiting responses (recorded from `send_response`, which only
+    /// borrows the service)
+    batches: RefCell<BatchTable>,
+    /// Client context of the batch operation being handled
+    batch_ctx: Option<ClientContext>,
+    /// Trash entry naming and the background purge of old entries
+    trash: TrashSweep,
+    /// Tells apart snapshots taken in the same millisecond
+    snapshot_seq: u32,
+    /// Whether whole-file reads are checked against the inode's content
+    /// hash (the `vfs.verify_hashes` flag)
+    verify_hashes: bool,
+    /// Finds storage operations whose result never arrives
+    watchdog: Watchdog,
+    /// Background collection of orphaned content records and blobs
+    gc: GcSweep,
+    /// Counters of operations that never got their result, and of what
+    /// collection reclaimed
+    stats: VfsStats,
+    /// Whether `stats` changed since last published
+    stats_dirty: bool,
+}
+
+impl Default for VfsService {
+    fn default() -> Self {
+        Self {
+            registered: false,
+            pending_ops: BTreeMap::new(),
+            op_deadlines: BTreeMap::new(),
+            op_timeouts: BTreeMap::new(),
+            request_deadline: None,
+            migration: MigrationSweep::default(),
+            checkpoint: StatefulService::new("vfs", StateStore::Storage),
+            drain: Drain::default(),
+            service_keys: TrustedServiceKeys::default(),
+            names: CallerNames::default(),
+            handles: HandleTable::default(),
+            watches: WatchTable::default(),
+            quotas: QuotaTable::default(),
+            content_keys: ContentKeys::default(),
+            blob_releases: Vec::new(),
+            mounts: MountSet::default(),
+            callers: CallerUsers::default(),
+            batches: RefCell::new(BatchTable::default()),
+            batch_ctx: None,
+            trash: TrashSweep::default(),
+            snapshot_seq: 0,
+            verify_hashes: false,
+            watchdog: Watchdog::default(),
+            gc: GcSweep::default(),
+            stats: VfsStats::default(),
+            stats_dirty: false,
+        }
+    }
 }
 
 // =============================================================================
@@ -485,32 +1263,39 @@ pub fn build_parent_paths(path: &str) -> Vec<String> {
 // Permission Context Derivation
 // =============================================================================
 
-/// Derive PermissionContext from the calling process PID and target path.
+/// Derive PermissionContext from the calling process and target path.
 ///
 /// # Permission Model
 ///
-/// - **System processes** (PID 1-9): Full access (system class)
-///   - User ID still extracted from path for ownership assignment
-/// - **User applications** (PID >= 10): Check owner/world permissions
-///   - User ID extracted from path: `/users/{user_id}/...` or `/home/{user_id}/...`
-///   - If path doesn't contain user ID, treated as "other" (world permissions)
-pub fn derive_permission_context(from_pid: u32, path: &str) -> PermissionContext {
-    // Extract user ID from path if present (needed for ownership assignment)
-    // Paths like /users/12345/... or /home/12345/... contain the user ID
-    let user_id = extract_user_id_from_path(path);
-
-    // System processes (init, vfs, identity, time services) have system class
-    // but still use path-extracted user_id for setting file ownership
-    if from_pid < 10 {
-        return PermissionContext {
-            user_id,
+/// - **Init**: Superuser, passes every check
+/// - **System processes** (the supervisor and boot services, recognized by
+///   the name they run under, see `crate::trust`): Full access (system class)
+///   - For both, user ID extracted from path for ownership assignment:
+///     `/users/{user_id}/...` or `/home/{user_id}/...`
+/// - **User applications**: Check owner/world permissions
+///   - User ID is the one the caller acts as (see `handlers::callers`),
+///     whatever the path names
+///   - Without one, treated as "other" (world permissions)
+pub fn derive_permission_context(
+    class: CallerClass,
+    path: &str,
+    callers: &CallerUsers,
+) -> PermissionContext {
+    match class {
+        CallerClass::Init => PermissionContext {
+            user_id: extract_user_id_from_path(path),
+            process_class: ProcessClass::Superuser,
+        },
+        // System processes (vfs, identity, time services, ...) have system
+        // class and use the path-extracted user_id for setting file ownership
+        class if class.is_system() => PermissionContext {
+            user_id: extract_user_id_from_path(path),
             process_class: ProcessClass::System,
-        };
-    }
-
-    PermissionContext {
-        user_id,
-        process_class: ProcessClass::Application,
+        },
+        _ => PermissionContext {
+            user_id: callers.user(),
+            process_class: ProcessClass::Application,
+        },
     }
 }
 
@@ -536,6 +1321,16 @@ fn extract_user_id_from_path(path: &str) -> Option<u128> {
 }
 
 impl VfsService {
+    /// The PermissionContext `from_pid`'s request for `path` is checked with.
+    pub fn permission_context(&self, from_pid: u32, path: &str) -> PermissionContext {
+        derive_permission_context(self.names.class_of(from_pid), path, &self.callers)
+    }
+
+    /// Whether `pid` is checked as a system process, acting for every user.
+    pub fn is_system_caller(&self, pid: u32) -> bool {
+        self.names.class_of(pid).is_system()
+    }
+
     // =========================================================================
     // Storage syscall helpers
     // =========================================================================
@@ -553,12 +1348,12 @@ impl VfsService {
 
         match syscall::storage_read_async(key) {
             Ok(request_id) => {
-                let request_id = request_id as u32;
                 syscall::debug(&format!(
                     "VfsService: storage_read_async({}) -> request_id={}",
                     key, request_id
                 ));
                 self.pending_ops.insert(request_id, pending_op);
+                self.track_deadline(request_id);
                 Ok(())
             }
             Err(e) => {
@@ -586,7 +1381,6 @@ impl VfsService {
 
         match syscall::storage_write_async(key, value) {
             Ok(request_id) => {
-                let request_id = request_id as u32;
                 syscall::debug(&format!(
                     "VfsService: storage_write_async({}, {} bytes) -> request_id={}",
                     key,
@@ -594,6 +1388,7 @@ impl VfsService {
                     request_id
                 ));
                 self.pending_ops.insert(request_id, pending_op);
+                self.track_deadline(request_id);
                 Ok(())
             }
             Err(e) => {
@@ -620,12 +1415,12 @@ impl VfsService {
 
         match syscall::storage_delete_async(key) {
             Ok(request_id) => {
-                let request_id = request_id as u32;
                 syscall::debug(&format!(
                     "VfsService: storage_delete_async({}) -> request_id={}",
                     key, request_id
                 ));
                 self.pending_ops.insert(request_id, pending_op);
+                self.track_deadline(request_id);
                 Ok(())
             }
             Err(e) => {
@@ -635,6 +1430,42 @@ impl VfsService {
         }
     }
 
+    /// Start async batch storage write and track the pending operation
+    ///
+    /// The items are written in one storage transaction: all of them land
+    /// or none do.
+    pub fn start_storage_batch_write(
+        &mut self,
+        items: &[(&str, &[u8])],
+        pending_op: PendingOp,
+    ) -> Result<(), AppError> {
+        // Rule 11: Check resource limit before starting new operation
+        if self.pending_ops.len() >= MAX_PENDING_OPS {
+            syscall::debug(&format!(
+                "VfsService: Too many pending operations ({}), rejecting batch write",
+                self.pending_ops.len()
+            ));
+            return Err(AppError::IpcError("Too many pending operations".into()));
+        }
+
+        match syscall::storage_batch_write_async(items) {
+            Ok(request_id) => {
+                syscall::debug(&format!(
+                    "VfsService: storage_batch_write_async({} items) -> request_id={}",
+                    items.len(),
+                    request_id
+                ));
+                self.pending_ops.insert(request_id, pending_op);
+                self.track_deadline(request_id);
+                Ok(())
+            }
+            Err(e) => {
+                syscall::debug(&format!("VfsService: storage_batch_write_async failed: {}", e));
+                Err(AppError::IpcError(format!("Storage batch write failed: {}", e)))
+            }
+        }
+    }
+
     /// Start async storage list and track the pending operation
     pub fn start_storage_list(
         &mut self,
@@ -652,12 +1483,12 @@ impl VfsService {
 
         match syscall::storage_list_async(prefix) {
             Ok(request_id) => {
-                let request_id = request_id as u32;
                 syscall::debug(&format!(
                     "VfsService: storage_list_async({}) -> request_id={}",
                     prefix, request_id
                 ));
                 self.pending_ops.insert(request_id, pending_op);
+                self.track_deadline(request_id);
                 Ok(())
             }
             Err(e) => {
@@ -684,12 +1515,12 @@ impl VfsService {
 
         match syscall::storage_exists_async(key) {
             Ok(request_id) => {
-                let request_id = request_id as u32;
                 syscall::debug(&format!(
                     "VfsService: storage_exists_async({}) -> request_id={}",
                     key, request_id
                 ));
                 self.pending_ops.insert(request_id, pending_op);
+                self.track_deadline(request_id);
                 Ok(())
             }
             Err(e) => {
@@ -703,28 +1534,48 @@ impl VfsService {
     // Storage result handler (main dispatcher)
     // =========================================================================
 
-    /// Handle MSG_STORAGE_RESULT - async storage operation completed
-    fn handle_storage_result(&mut self, ctx: &AppContext, msg: &Message) -> Result<(), AppError> {
-        // Parse storage result
-        // Format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
-        if msg.data.len() < 9 {
-            syscall::debug("VfsService: storage result too short");
-            return Ok(());
+    /// Handle MSG_STORAGE_RESULT_BATCH - several storage operations completed
+    fn handle_storage_result_batch(
+        &mut self,
+        ctx: &AppContext,
+        msg: &Message,
+    ) -> Result<(), AppError> {
+        // Format: [count: u32, (len: u32, result: [u8; len])*]
+        let results = match split_storage_result_batch(&msg.data) {
+            Some(results) => results,
+            None => {
+                syscall::debug("VfsService: malformed storage result batch");
+                return Ok(());
+            }
+        };
+        for result in results {
+            // One failing entry must not lose the rest of the batch
+            if let Err(e) = self.handle_storage_result(ctx, result) {
+                syscall::debug(&format!("VfsService: batched storage result failed: {:?}", e));
+            }
         }
+        Ok(())
+    }
 
-        let request_id = u32::from_le_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
-        let result_type = msg.data[4];
-        let data_len =
-            u32::from_le_bytes([msg.data[5], msg.data[6], msg.data[7], msg.data[8]]) as usize;
-        let data = if data_len > 0 && msg.data.len() >= 9 + data_len {
-            &msg.data[9..9 + data_len]
-        } else {
-            &[]
+    /// Handle MSG_STORAGE_RESULT - async storage operation completed
+    fn handle_storage_result(&mut self, ctx: &AppContext, payload: &[u8]) -> Result<(), AppError> {
+        let StorageResult {
+            request_id,
+            kind: result_type,
+            data,
+        } = match StorageResult::decode(payload) {
+            Ok(result) => result,
+            Err(e) => {
+                syscall::debug(&format!("VfsService: malformed storage result: {:?}", e));
+                return Ok(());
+            }
         };
 
         syscall::debug(&format!(
             "VfsService: storage result request_id={}, type={}, data_len={}",
-            request_id, result_type, data_len
+            request_id,
+            result_type.name(),
+            data.len()
         ));
 
         // Look up pending operation
@@ -735,8 +1586,32 @@ impl VfsService {
                 return Ok(());
             }
         };
+        if !self.resume_deadline(request_id, ctx.uptime_ns) {
+            syscall::debug(&format!(
+                "VfsService: request_id {} is past its deadline, dropping",
+                request_id
+            ));
+            return Ok(());
+        }
+        let Some(pending_op) = self.park_for_content_key(pending_op, result_type, data) else {
+            return Ok(());
+        };
 
-        // Dispatch based on operation type and result
+        self.dispatch_storage_result(ctx, pending_op, result_type, data)
+    }
+
+    /// Continue the operation a storage result belongs to.
+    fn dispatch_storage_result(
+        &mut self,
+        ctx: &AppContext,
+        pending_op: PendingOp,
+        result_type: ResultKind,
+        data: &[u8],
+    ) -> Result<(), AppError> {
+        // A link record stands for the blob it names
+        if result_type == ResultKind::ReadOk && is_link(data) && pending_op.follows_links() {
+            return self.follow_link(pending_op, data);
+        }
         match pending_op {
             PendingOp::GetInode {
                 ctx: client_ctx,
@@ -744,9 +1619,14 @@ impl VfsService {
                 op_type,
                 perm_ctx,
             } => self.handle_inode_result(ctx, &client_ctx, &path, op_type, &perm_ctx, result_type, data),
-            PendingOp::GetContent { ctx: client_ctx, path, perm_ctx: _ } => {
+            PendingOp::GetContent {
+                ctx: client_ctx,
+                path,
+                perm_ctx: _,
+                expected_hash,
+            } => {
                 // Permission already checked during inode fetch
-                self.handle_content_result(&client_ctx, &path, result_type, data)
+                self.handle_content_result(&client_ctx, &path, expected_hash, result_type, data)
             }
             PendingOp::PutInode {
                 ctx: client_ctx,
@@ -786,7 +1666,61 @@ impl VfsService {
                 path,
                 perm_ctx,
                 stage,
-            } => self.handle_write_file_op_result(&client_ctx, &path, &perm_ctx, stage, result_type, data),
+                reply,
+            } => self.handle_write_file_op_result(&client_ctx, &path, &perm_ctx, stage, reply, result_type, data),
+            PendingOp::OpenFileOp {
+                ctx: client_ctx,
+                path,
+                perm_ctx,
+                writable,
+                create,
+            } => self.handle_open_result(&client_ctx, &path, &perm_ctx, writable, create, result_type, data),
+            PendingOp::WatchOp {
+                ctx: client_ctx,
+                path,
+                perm_ctx,
+                recursive,
+            } => self.handle_watch_result(&client_ctx, &path, &perm_ctx, recursive, result_type, data),
+            PendingOp::ReadAtOp {
+                ctx: client_ctx,
+                offset,
+                length,
+                shm,
+            } => self.handle_read_at_result(&client_ctx, offset, length, shm, result_type, data),
+            PendingOp::WriteAtOp {
+                ctx: client_ctx,
+                path,
+                perm_ctx,
+                offset,
+                data: write,
+            } => self.handle_write_at_result(client_ctx, path, perm_ctx, offset, write, result_type, data),
+            PendingOp::HashOp {
+                ctx: client_ctx,
+                path,
+                perm_ctx,
+                stage,
+            } => self.handle_hash_op_result(client_ctx, path, perm_ctx, stage, result_type, data),
+            PendingOp::ChunkReadOp { ctx: client_ctx, read } => {
+                self.handle_chunk_read_result(client_ctx, read, result_type, data)
+            }
+            PendingOp::ChunkPatchOp {
+                ctx: client_ctx,
+                patch,
+                stage,
+            } => self.handle_chunk_patch_result(client_ctx, patch, stage, result_type, data),
+            PendingOp::ContentDeleteOp { path, stage, then } => {
+                self.handle_content_delete_result(ctx, path, stage, *then, result_type, data)
+            }
+            PendingOp::ReleaseChunks { manifest, next } => {
+                self.handle_release_chunks_result(manifest, next, result_type)
+            }
+            PendingOp::FollowLink { then } => {
+                self.handle_follow_link_result(ctx, *then, result_type, data)
+            }
+            PendingOp::ReleaseBlob { hash, stage } => {
+                self.handle_release_blob_result(hash, stage, result_type, data)
+            }
+            PendingOp::GcOp { stage } => self.handle_gc_result(stage, result_type, data),
             PendingOp::MkdirOp {
                 ctx: client_ctx,
                 path,
@@ -806,6 +1740,59 @@ impl VfsService {
                 perm_ctx,
                 stage,
             } => self.handle_unlink_op_result(&client_ctx, &path, &perm_ctx, stage, result_type, data),
+            PendingOp::RenameOp {
+                ctx: client_ctx,
+                from,
+                to,
+                perm_ctx,
+                stage,
+                reply,
+            } => self.handle_rename_op_result(client_ctx, from, to, perm_ctx, stage, reply, result_type, data),
+            PendingOp::LinkOp {
+                ctx: client_ctx,
+                path,
+                link_path,
+                perm_ctx,
+                stage,
+            } => self.handle_link_op_result(client_ctx, path, link_path, perm_ctx, stage, result_type, data),
+            PendingOp::RmdirOp {
+                ctx: client_ctx,
+                path,
+                perm_ctx,
+                recursive,
+                walk,
+                stage,
+            } => self.handle_rmdir_op_result(client_ctx, path, perm_ctx, recursive, walk, stage, result_type, data),
+            PendingOp::XattrOp {
+                ctx: client_ctx,
+                path,
+                perm_ctx,
+                request,
+                stage,
+            } => self.handle_xattr_op_result(client_ctx, path, perm_ctx, request, stage, result_type, data),
+            PendingOp::TrashOp {
+                ctx: client_ctx,
+                user_id,
+                perm_ctx,
+                request,
+                scan,
+                stage,
+            } => self.handle_trash_op_result(client_ctx, user_id, perm_ctx, request, scan, stage, result_type, data),
+            PendingOp::TrashSweepHomes => self.handle_trash_sweep_homes_result(result_type, data),
+            PendingOp::SnapshotOp { ctx: client_ctx, job, stage } => self.handle_snapshot_op_result(client_ctx, job, stage, result_type, data),
+            PendingOp::MigrateInode { path } => {
+                self.handle_migrate_inode_result(&path, result_type, data)
+            }
+            PendingOp::MigrateList { path } => {
+                self.handle_migrate_list_result(&path, result_type, data)
+            }
+            PendingOp::MigrateWrite { path } => self.handle_migrate_write_result(&path, result_type),
+            PendingOp::MigrateSeal { path, inode, stage } => {
+                self.handle_migrate_seal_result(&path, inode, stage, result_type, data)
+            }
+            PendingOp::RestoreState => self.handle_restore_state_result(result_type, data),
+            PendingOp::LoadFlags => self.handle_load_flags_result(result_type, data),
+            PendingOp::CheckpointState => self.handle_checkpoint_state_result(result_type),
         }
     }
 
@@ -817,7 +1804,7 @@ impl VfsService {
         path: &str,
         op_type: InodeOpType,
         perm_ctx: &PermissionContext,
-        result_type: u8,
+        result_type: ResultKind,
         data: &[u8],
     ) -> Result<(), AppError> {
         match op_type {
@@ -832,8 +1819,8 @@ impl VfsService {
             InodeOpType::WriteFileCheckParent { content } => {
                 self.handle_write_file_inode_result(client_ctx, path, perm_ctx, result_type, data, content)
             }
-            InodeOpType::Rmdir { recursive: _ } => {
-                self.handle_rmdir_inode_result(client_ctx, path, perm_ctx, result_type, data)
+            InodeOpType::Rmdir { recursive } => {
+                self.handle_rmdir_inode_result(client_ctx, path, perm_ctx, recursive, result_type, data)
             }
             InodeOpType::Unlink => {
                 self.handle_unlink_inode_result(client_ctx, path, perm_ctx, result_type, data)
@@ -867,12 +1854,20 @@ impl VfsService {
     ) -> Result<(), AppError> {
         match serde_json::to_vec(response) {
             Ok(data) => {
+                // Part of a batch: answered with the rest of it
+                if let Some(slot) = &ctx.batch {
+                    return self.record_batch_entry(slot, tag, data);
+                }
+
                 // Try direct IPC via reply capability first
                 if let Some(&reply_slot) = ctx.reply_caps.first() {
                     syscall::debug(&format!(
                         "VfsService: Sending response via reply cap slot {} (tag 0x{:x})",
                         reply_slot, tag
                     ));
+                    // Storage results received since the request replaced
+                    // its correlation ID
+                    syscall::set_correlation_id(ctx.correlation_id);
                     match syscall::send(reply_slot, tag, &data) {
                         Ok(()) => {
                             syscall::debug("VfsService: Response sent via reply cap");
@@ -909,6 +1904,9 @@ impl VfsService {
         tag: u32,
         response: &T,
     ) -> Result<(), AppError> {
+        if let Some(ctx) = self.batch_reply_to(to_pid, tag) {
+            return self.send_response(ctx, tag, response);
+        }
         match serde_json::to_vec(response) {
             Ok(data) => {
                 let hex: String = data.iter().map(|b| format!("{:02x}", b)).collect();
@@ -950,10 +1948,38 @@ impl ZeroApp for VfsService {
 
         syscall::debug("VfsService: Registered with init");
 
+        // Scratch space under /tmp stays in memory
+        if let Err(e) = self.mounts.mount_tmp() {
+            syscall::debug(&format!("VfsService: Failed to mount /tmp: {:?}", e));
+        }
+        let _ = syscall::send(syscall::INIT_ENDPOINT_SLOT, syscall::MSG_SERVICE_READY, &[]);
+
+        // Restore state from the previous run; this then starts (or resumes)
+        // the sweep that upgrades inodes left behind by older builds
+        self.start_state_restore();
+        self.start_flags_load();
+
+        if let Some(slot) = ctx.input_endpoint {
+            if let Err(e) = self.watchdog.arm(slot, DEFAULT_WATCHDOG_PERIOD_MS) {
+                syscall::debug(&format!("VfsService: No watchdog: {}", e));
+            }
+        }
+
         Ok(())
     }
 
-    fn update(&mut self, _ctx: &AppContext) -> ControlFlow {
+    fn update(&mut self, ctx: &AppContext) -> ControlFlow {
+        // Background work runs on behalf of no client
+        self.request_deadline = None;
+        self.expire_pending_ops(ctx.uptime_ns);
+        self.pump_migration_sweep();
+        self.pump_blob_releases();
+        let now_ms = ctx.uptime_ns / 1_000_000;
+        self.pump_trash_sweep(now_ms);
+        self.pump_gc(now_ms);
+        self.pump_checkpoint(now_ms);
+        self.pump_drain(now_ms);
+        self.publish_stats();
         ControlFlow::Yield
     }
 
@@ -963,16 +1989,101 @@ impl ZeroApp for VfsService {
             msg.tag, msg.from_pid
         ));
 
+        if self.watchdog.is_tick(&msg) {
+            self.abort_stuck_ops();
+            return Ok(());
+        }
+
+        // Storage results continue the deadline of the request they belong to
+        if msg.tag != MSG_STORAGE_RESULT && msg.tag != MSG_STORAGE_RESULT_BATCH {
+            self.request_deadline = syscall::current_deadline();
+        }
+
+        // Paths under memory, asset or read-only mounts are answered here
+        if !self.drain.is_draining() {
+            if let Some(result) = self.hold_for_snapshot(&msg) {
+                return result;
+            }
+            if let Some(result) = self.route_to_mount(&msg) {
+                return result;
+            }
+        }
+
         match msg.tag {
-            MSG_STORAGE_RESULT => self.handle_storage_result(ctx, &msg),
+            MSG_STORAGE_RESULT => self.handle_storage_result(ctx, &msg.data),
+            MSG_STORAGE_RESULT_BATCH => self.handle_storage_result_batch(ctx, &msg),
+            syscall::init::MSG_SERVICE_DRAIN => self.handle_drain(&msg),
+            zos_ipc::request::MSG_CANCEL_REQUEST => self.handle_cancel(&msg),
+            vfs_msg::MSG_VFS_MKDIR
+            | vfs_msg::MSG_VFS_RMDIR
+            | vfs_msg::MSG_VFS_READDIR
+            | vfs_msg::MSG_VFS_WRITE
+            | vfs_msg::MSG_VFS_READ
+            | vfs_msg::MSG_VFS_UNLINK
+            | vfs_msg::MSG_VFS_RENAME
+            | vfs_msg::MSG_VFS_LINK
+            | vfs_msg::MSG_VFS_STAT
+            | vfs_msg::MSG_VFS_EXISTS
+            | vfs_msg::MSG_VFS_SIGNED_REQUEST
+            | vfs_msg::MSG_VFS_OPEN
+            | vfs_msg::MSG_VFS_READ_AT
+            | vfs_msg::MSG_VFS_WRITE_AT
+            | vfs_msg::MSG_VFS_WATCH
+            | vfs_msg::MSG_VFS_BATCH
+            | vfs_msg::MSG_VFS_SETXATTR
+            | vfs_msg::MSG_VFS_GETXATTR
+            | vfs_msg::MSG_VFS_LISTXATTR
+            | vfs_msg::MSG_VFS_TRASH_LIST
+            | vfs_msg::MSG_VFS_TRASH_RESTORE
+            | vfs_msg::MSG_VFS_TRASH_PURGE
+            | vfs_msg::MSG_VFS_SNAPSHOT_CREATE
+            | vfs_msg::MSG_VFS_SNAPSHOT_LIST
+            | vfs_msg::MSG_VFS_SNAPSHOT_RESTORE
+            | vfs_msg::MSG_VFS_SNAPSHOT_DELETE
+            | vfs_msg::MSG_VFS_HASH
+                if self.drain.is_draining() =>
+            {
+                self.refuse_while_draining(&msg)
+            }
             vfs_msg::MSG_VFS_MKDIR => self.handle_mkdir(ctx, &msg),
             vfs_msg::MSG_VFS_RMDIR => self.handle_rmdir(ctx, &msg),
             vfs_msg::MSG_VFS_READDIR => self.handle_readdir(ctx, &msg),
             vfs_msg::MSG_VFS_WRITE => self.handle_write(ctx, &msg),
             vfs_msg::MSG_VFS_READ => self.handle_read(ctx, &msg),
             vfs_msg::MSG_VFS_UNLINK => self.handle_unlink(ctx, &msg),
+            vfs_msg::MSG_VFS_RENAME => self.handle_rename(ctx, &msg),
+            vfs_msg::MSG_VFS_LINK => self.handle_link(ctx, &msg),
             vfs_msg::MSG_VFS_STAT => self.handle_stat(ctx, &msg),
             vfs_msg::MSG_VFS_EXISTS => self.handle_exists(ctx, &msg),
+            vfs_msg::MSG_VFS_SIGNED_REQUEST => self.handle_signed_request(ctx, &msg),
+            vfs_msg::MSG_VFS_REGISTER_SERVICE_KEY => self.handle_register_service_key(&msg),
+            vfs_msg::MSG_VFS_OPEN => self.handle_open(ctx, &msg),
+            vfs_msg::MSG_VFS_READ_AT => self.handle_read_at(ctx, &msg),
+            vfs_msg::MSG_VFS_WRITE_AT => self.handle_write_at(ctx, &msg),
+            vfs_msg::MSG_VFS_CLOSE => self.handle_close(&msg),
+            vfs_msg::MSG_VFS_ATTACH_SHM => self.handle_attach_shm(&msg),
+            vfs_msg::MSG_VFS_WATCH => self.handle_watch(ctx, &msg),
+            vfs_msg::MSG_VFS_UNWATCH => self.handle_unwatch(&msg),
+            vfs_msg::MSG_VFS_QUOTA_STAT => self.handle_quota_stat(&msg),
+            vfs_msg::MSG_VFS_MOUNT => self.handle_mount(&msg),
+            vfs_msg::MSG_VFS_UMOUNT => self.handle_umount(&msg),
+            vfs_msg::MSG_VFS_MOUNTS => self.handle_mounts(&msg),
+            vfs_msg::MSG_VFS_SET_CALLER_USER => self.handle_set_caller_user(&msg),
+            vfs_msg::MSG_VFS_BATCH => self.handle_batch(ctx, &msg),
+            vfs_msg::MSG_VFS_SETXATTR => self.handle_setxattr(&msg),
+            vfs_msg::MSG_VFS_GETXATTR => self.handle_getxattr(&msg),
+            vfs_msg::MSG_VFS_LISTXATTR => self.handle_listxattr(&msg),
+            vfs_msg::MSG_VFS_TRASH_LIST => self.handle_trash_list(&msg),
+            vfs_msg::MSG_VFS_TRASH_RESTORE => self.handle_trash_restore(&msg),
+            vfs_msg::MSG_VFS_TRASH_PURGE => self.handle_trash_purge(&msg),
+            vfs_msg::MSG_VFS_SNAPSHOT_CREATE => self.handle_snapshot_create(&msg),
+            vfs_msg::MSG_VFS_SNAPSHOT_LIST => self.handle_snapshot_list(&msg),
+            vfs_msg::MSG_VFS_SNAPSHOT_RESTORE => self.handle_snapshot_restore(&msg),
+            vfs_msg::MSG_VFS_SNAPSHOT_DELETE => self.handle_snapshot_delete(&msg),
+            vfs_msg::MSG_VFS_HASH => self.handle_hash(&msg),
+            tag if keystore_async::is_keystore_response(tag) => {
+                self.handle_keystore_response(ctx, &msg)
+            }
             _ => {
                 syscall::debug(&format!("VfsService: Unknown message tag 0x{:x}", msg.tag));
                 Ok(())
@@ -12,7 +12,7 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::services::vfs::{ClientContext, InodeOpType, PendingOp, VfsService, validate_path, MAX_PENDING_OPS};
+    use crate::services::vfs::{derive_permission_context, ClientContext, InodeOpType, PendingOp, VfsService, validate_path, MAX_PENDING_OPS};
     use alloc::string::String;
     use alloc::vec::Vec;
     use zos_vfs::service::{PermissionContext, ProcessClass};
@@ -21,6 +21,8 @@ mod tests {
         ClientContext {
             pid,
             reply_caps: Vec::new(),
+            batch: None,
+            correlation_id: None,
         }
     }
 
@@ -64,6 +66,7 @@ mod tests {
                 ctx: make_test_client_ctx(3),
                 path: String::from("/tmp/c"),
                 perm_ctx: make_test_perm_ctx(),
+                expected_hash: None,
             },
         );
 
@@ -95,6 +98,154 @@ mod tests {
         assert!(service.pending_ops.is_empty());
     }
 
+    #[test]
+    fn test_expire_pending_ops_past_deadline() {
+        let mut service = VfsService::default();
+        for (request_id, deadline) in [(1, Some(100)), (2, Some(300)), (3, None)] {
+            service.pending_ops.insert(
+                request_id,
+                PendingOp::ExistsCheck {
+                    ctx: make_test_client_ctx(10),
+                    path: String::from("/tmp/x"),
+                },
+            );
+            service.request_deadline = deadline;
+            service.track_deadline(request_id);
+        }
+
+        service.expire_pending_ops(200);
+        let keys: Vec<u32> = service.pending_ops.keys().copied().collect();
+        assert_eq!(keys, vec![2, 3]);
+
+        // A result arriving in time resumes under the request's deadline
+        assert!(service.resume_deadline(2, 250));
+        assert_eq!(service.request_deadline, Some(300));
+        assert!(service.op_deadlines.is_empty());
+        assert_eq!(service.stats.past_deadline, 1);
+    }
+
+    #[test]
+    fn test_time_out_pending_ops() {
+        use crate::services::vfs::OP_TIMEOUT_NS;
+
+        let mut service = VfsService::default();
+        service.pending_ops.insert(
+            1,
+            PendingOp::ExistsCheck {
+                ctx: make_test_client_ctx(10),
+                path: String::from("/tmp/x"),
+            },
+        );
+        service.pending_ops.insert(2, PendingOp::CheckpointState);
+        // Natively the clock reads 0, so both are due at OP_TIMEOUT_NS
+        service.request_deadline = None;
+        service.track_deadline(1);
+        service.track_deadline(2);
+
+        service.expire_pending_ops(OP_TIMEOUT_NS - 1);
+        assert_eq!(service.pending_ops.len(), 2);
+        assert_eq!(service.stats.timed_out, 0);
+
+        // Overdue: dropped, with or without a client to answer
+        service.expire_pending_ops(OP_TIMEOUT_NS);
+        assert!(service.pending_ops.is_empty());
+        assert!(service.op_timeouts.is_empty());
+        assert_eq!(service.stats.timed_out, 2);
+
+        assert!(service.stats_dirty);
+        service.publish_stats();
+        assert!(!service.stats_dirty);
+        assert_eq!(service.stats.pending_ops, 0);
+    }
+
+    #[test]
+    fn test_abort_stuck_ops() {
+        let mut service = VfsService::default();
+        let exists = || PendingOp::ExistsCheck {
+            ctx: make_test_client_ctx(10),
+            path: String::from("/tmp/x"),
+        };
+        service.pending_ops.insert(1, exists());
+        service.abort_stuck_ops();
+        service.pending_ops.insert(2, exists());
+
+        // Natively the HAL knows no requests, so stuck ones are dropped
+        service.abort_stuck_ops();
+        let keys: Vec<u32> = service.pending_ops.keys().copied().collect();
+        assert_eq!(keys, vec![2]);
+        service.abort_stuck_ops();
+        assert!(service.pending_ops.is_empty());
+        assert_eq!(service.stats.aborted, 2);
+    }
+
+    #[test]
+    fn test_split_storage_result_batch() {
+        use crate::services::vfs::split_storage_result_batch;
+
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&2u32.to_le_bytes());
+        for result in [&b"first"[..], &b"second!"[..]] {
+            payload.extend_from_slice(&(result.len() as u32).to_le_bytes());
+            payload.extend_from_slice(result);
+        }
+        let results = split_storage_result_batch(&payload).expect("batch should parse");
+        assert_eq!(results, vec![&b"first"[..], &b"second!"[..]]);
+
+        // Truncated entries and oversized counts are rejected, not read past
+        assert!(split_storage_result_batch(&payload[..payload.len() - 1]).is_none());
+        assert!(split_storage_result_batch(&u32::MAX.to_le_bytes()).is_none());
+        assert!(split_storage_result_batch(&[1, 0]).is_none());
+    }
+
+    #[test]
+    fn test_cancel_pending_ops_matches_sender_and_tag() {
+        use zos_ipc::request::CANCEL_ALL;
+        use zos_vfs::ipc::vfs_msg;
+
+        let mut service = VfsService::default();
+        service.pending_ops.insert(
+            1,
+            PendingOp::GetContent {
+                ctx: make_test_client_ctx(10),
+                path: String::from("/tmp/big"),
+                perm_ctx: make_test_perm_ctx(),
+                expected_hash: None,
+            },
+        );
+        service.pending_ops.insert(
+            2,
+            PendingOp::ExistsCheck {
+                ctx: make_test_client_ctx(10),
+                path: String::from("/tmp/x"),
+            },
+        );
+        service.pending_ops.insert(
+            3,
+            PendingOp::GetContent {
+                ctx: make_test_client_ctx(11),
+                path: String::from("/tmp/other"),
+                perm_ctx: make_test_perm_ctx(),
+                expected_hash: None,
+            },
+        );
+        service.pending_ops.insert(4, PendingOp::CheckpointState);
+
+        // Only PID 10's read is cancelled, and it is owed a read response
+        let replies = service.cancel_pending_ops(10, vfs_msg::MSG_VFS_READ);
+        assert_eq!(
+            replies.into_iter().collect::<Vec<_>>(),
+            vec![(10, vfs_msg::MSG_VFS_READ_RESPONSE)]
+        );
+        let keys: Vec<u32> = service.pending_ops.keys().copied().collect();
+        assert_eq!(keys, vec![2, 3, 4]);
+
+        // CANCEL_ALL leaves other clients and internal operations alone
+        let replies = service.cancel_pending_ops(10, CANCEL_ALL);
+        assert_eq!(replies.len(), 1);
+        let keys: Vec<u32> = service.pending_ops.keys().copied().collect();
+        assert_eq!(keys, vec![3, 4]);
+    }
+
     #[test]
     fn test_validate_path_valid() {
         assert!(validate_path("/").is_ok());
@@ -242,10 +393,121 @@ mod tests {
         assert_eq!(perm_ctx.user_id, Some(12345));
     }
 
+    #[test]
+    fn test_application_acts_as_session_user() {
+        let mut service = VfsService::default();
+        // Nobody signed in: world permissions only, whatever the path names
+        let ctx = service.permission_context(20, "/home/1/notes.txt");
+        assert!(matches!(ctx.process_class, ProcessClass::Application));
+        assert_eq!(ctx.user_id, None);
+
+        service.callers.set(Some(1));
+        assert_eq!(service.permission_context(20, "/home/2/a").user_id, Some(1));
+        assert_eq!(service.permission_context(21, "/home/1/a").user_id, Some(1));
+
+        // Logout: back to world permissions
+        service.callers.set(None);
+        assert_eq!(service.permission_context(21, "/home/1/a").user_id, None);
+    }
+
+    #[test]
+    fn test_init_is_superuser() {
+        let service = VfsService::default();
+        let init = service.permission_context(1, "/home/7/a");
+        assert!(matches!(init.process_class, ProcessClass::Superuser));
+        assert_eq!(init.user_id, Some(7));
+        let supervisor = service.permission_context(0, "/home/7/a");
+        assert!(matches!(supervisor.process_class, ProcessClass::System));
+    }
+
+    #[test]
+    fn test_system_class_follows_service_name() {
+        use crate::trust::CallerClass;
+        use crate::services::vfs::handlers::callers::CallerUsers;
+
+        let service = VfsService::default();
+        // Boot services past PID 9, or restarted under a fresh PID
+        service.names.remember(12, "events");
+        service.names.remember(31, "vfs");
+        for pid in [12, 31] {
+            let ctx = service.permission_context(pid, "/home/7/a");
+            assert!(matches!(ctx.process_class, ProcessClass::System));
+            assert_eq!(ctx.user_id, Some(7));
+            assert!(service.is_system_caller(pid));
+        }
+
+        // A low PID is no longer a reason for trust
+        service.names.remember(3, "terminal");
+        let app = service.permission_context(3, "/home/7/a");
+        assert!(matches!(app.process_class, ProcessClass::Application));
+        assert_eq!(app.user_id, None);
+        assert!(!service.is_system_caller(3));
+
+        let callers = CallerUsers::default();
+        let desktop = derive_permission_context(CallerClass::Desktop, "/home/7/a", &callers);
+        assert!(matches!(desktop.process_class, ProcessClass::Application));
+    }
+
+    #[test]
+    fn test_caller_users_checkpoint() {
+        use crate::services::vfs::handlers::callers::CallerUsers;
+
+        let mut callers = CallerUsers::default();
+        callers.set(Some(u128::MAX));
+
+        // Full u128s survive the trip through serde_json::Value
+        let value = serde_json::to_value(&callers).unwrap();
+        let saved: CallerUsers = serde_json::from_value(value).unwrap();
+        assert_eq!(saved, callers);
+
+        // A session user set since the restart wins
+        let mut restored = CallerUsers::default();
+        restored.set(Some(6));
+        restored.restore(saved.clone());
+        assert_eq!(restored.user(), Some(6));
+        let mut fresh = CallerUsers::default();
+        fresh.restore(saved);
+        assert_eq!(fresh.user(), Some(u128::MAX));
+    }
+
     // =========================================================================
     // Resource Limit Tests (Rule 11)
     // =========================================================================
 
+    #[test]
+    fn test_service_key_registered_by_own_service_only() {
+        use crate::services::identity::utils::bytes_to_hex;
+        use crate::signing::SignedRequestError;
+        use crate::test_utils::mock_message;
+        use zos_vfs::ipc::{vfs_msg, RegisterServiceKeyRequest};
+
+        let register = |service: &mut VfsService, pid: u32, name: &str, key: [u8; 32]| {
+            let request = RegisterServiceKeyRequest {
+                service: String::from(name),
+                public_key: bytes_to_hex(&key),
+            };
+            let data = serde_json::to_vec(&request).unwrap();
+            let msg = mock_message(vfs_msg::MSG_VFS_REGISTER_SERVICE_KEY, pid, data);
+            service.handle_register_service_key(&msg).unwrap();
+        };
+
+        let mut service = VfsService::default();
+        // PermissionService restarted past PID 9 still registers its key
+        service.names.remember(14, "permission");
+        service.names.remember(3, "time");
+        register(&mut service, 14, "permission", [1u8; 32]);
+        assert_eq!(
+            service.service_keys.register("permission", [2u8; 32]),
+            Err(SignedRequestError::KeyMismatch(String::from("permission")))
+        );
+
+        // A key for another service, or from an unknown process, is refused
+        register(&mut service, 3, "flags", [1u8; 32]);
+        register(&mut service, 20, "update", [1u8; 32]);
+        assert_eq!(service.service_keys.register("flags", [2u8; 32]), Ok(()));
+        assert_eq!(service.service_keys.register("update", [2u8; 32]), Ok(()));
+    }
+
     #[test]
     fn test_max_pending_ops_constant() {
         // Ensure the constant is reasonable
@@ -291,8 +553,18 @@ mod tests {
         let stage1 = WriteFileStage::CheckingParent {
             content: vec![1, 2, 3],
         };
-        let stage2 = WriteFileStage::WritingContent { content_len: 100 };
-        let stage3 = WriteFileStage::WritingInode;
+        let stage2 = WriteFileStage::WritingContent {
+            content_len: 100,
+            encrypted: true,
+            content_hash: [0; 32],
+            reservation: Default::default(),
+            chunks: None,
+            replaced: None,
+            xattrs: Default::default(),
+        };
+        let stage3 = WriteFileStage::WritingInode {
+            reservation: Default::default(),
+        };
         
         // Verify we can clone stages
         let _cloned = stage1.clone();
@@ -331,8 +603,8 @@ mod tests {
         use crate::services::vfs::UnlinkStage;
         
         let stage1 = UnlinkStage::ReadingInode;
-        let stage2 = UnlinkStage::DeletingContent;
-        let stage3 = UnlinkStage::DeletingInode;
+        let stage2 = UnlinkStage::DeletingContent { charge: None };
+        let stage3 = UnlinkStage::DeletingInode { charge: None };
         
         // Verify we can clone stages
         let _cloned = stage1.clone();
@@ -426,6 +698,472 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pending_op_rename_op() {
+        use crate::services::vfs::{RenameReply, RenameStage};
+        use zos_vfs::ipc::vfs_msg;
+
+        let mut service = VfsService::default();
+        let op = PendingOp::RenameOp {
+            ctx: make_test_client_ctx(10),
+            from: String::from("/tmp/a"),
+            to: String::from("/home/b"),
+            perm_ctx: make_test_perm_ctx(),
+            stage: RenameStage::ReadingSource,
+            reply: RenameReply::Rename,
+        };
+        assert_eq!(op.client_reply(), Some((10, vfs_msg::MSG_VFS_RENAME_RESPONSE)));
+
+        // A rename rewrites inodes, so the migration sweep must wait for it
+        service.pending_ops.insert(1, op);
+        assert!(service.has_inode_mutation_in_flight());
+    }
+
+    /// Feeds storage results to a rename one stage at a time. The rename
+    /// answers in a batch slot so its response can be read back; the next
+    /// storage step it starts fails outside the kernel, which tells which
+    /// one it was.
+    struct RenameRun {
+        service: VfsService,
+        ctx: ClientContext,
+        from: String,
+        to: String,
+    }
+
+    impl RenameRun {
+        fn new(from: &str, to: &str) -> Self {
+            use crate::services::vfs::handlers::batch::BatchSlot;
+            use zos_vfs::ipc::vfs_msg;
+
+            let service = VfsService::default();
+            // Two operations, so the batch stays open after the rename answers
+            let id = service
+                .batches
+                .borrow_mut()
+                .start(make_test_client_ctx(20), 2, None)
+                .unwrap();
+            let mut ctx = make_test_client_ctx(20);
+            ctx.batch = Some(BatchSlot {
+                id,
+                index: 0,
+                response_tag: vfs_msg::MSG_VFS_RENAME_RESPONSE,
+            });
+            Self {
+                service,
+                ctx,
+                from: String::from(from),
+                to: String::from(to),
+            }
+        }
+
+        /// Feed one storage result; returns the storage step started next.
+        fn step(
+            &mut self,
+            stage: crate::services::vfs::RenameStage,
+            result_type: zos_ipc::storage::ResultKind,
+            data: &[u8],
+        ) -> Option<String> {
+            use crate::services::vfs::RenameReply;
+
+            let result = self.service.handle_rename_op_result(
+                self.ctx.clone(),
+                self.from.clone(),
+                self.to.clone(),
+                make_test_perm_ctx(),
+                stage,
+                RenameReply::Rename,
+                result_type,
+                data,
+            );
+            match result {
+                Ok(()) => None,
+                Err(zos_apps::AppError::IpcError(step)) => {
+                    Some(String::from(step.split(':').next().unwrap_or_default()))
+                }
+                Err(e) => panic!("unexpected error: {:?}", e),
+            }
+        }
+
+        /// The client's response, once the rename has answered.
+        fn response(&self) -> Option<Result<(), zos_vfs::VfsError>> {
+            let batches = self.service.batches.borrow();
+            let entry = batches.entry(self.ctx.batch.as_ref().unwrap())?;
+            let response: zos_vfs::ipc::RenameResponse =
+                serde_json::from_str(&entry.response).unwrap();
+            Some(response.result)
+        }
+    }
+
+    fn file_inode(path: &str) -> alloc::boxed::Box<zos_vfs::Inode> {
+        let (parent, name) = path.rsplit_once('/').unwrap();
+        alloc::boxed::Box::new(zos_vfs::Inode::new_file(
+            String::from(path),
+            String::from(if parent.is_empty() { "/" } else { parent }),
+            String::from(name),
+            None,
+            2,
+            None,
+            0,
+        ))
+    }
+
+    fn dir_inode(path: &str) -> alloc::boxed::Box<zos_vfs::Inode> {
+        let (parent, name) = path.rsplit_once('/').unwrap();
+        alloc::boxed::Box::new(zos_vfs::Inode::new_directory(
+            String::from(path),
+            String::from(if parent.is_empty() { "/" } else { parent }),
+            String::from(name),
+            None,
+            0,
+        ))
+    }
+
+    #[test]
+    fn test_rename_moves_file_across_directories() {
+        use crate::services::vfs::RenameStage;
+        use zos_ipc::storage::ResultKind;
+
+        let mut run = RenameRun::new("/home/a.txt", "/home/docs/b.txt");
+        let source = serde_json::to_vec(&file_inode("/home/a.txt")).unwrap();
+        let parent = serde_json::to_vec(&dir_inode("/home/docs")).unwrap();
+        let inode = || file_inode("/home/a.txt");
+
+        // Source read, then the target is checked
+        let next = run.step(RenameStage::ReadingSource, ResultKind::ReadOk, &source);
+        assert_eq!(next.as_deref(), Some("Storage read failed"));
+        // No target, so its parent is read
+        let next = run.step(
+            RenameStage::CheckingTarget { inode: inode() },
+            ResultKind::NotFound,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage read failed"));
+        // A writable directory: the content is read and written under /home/docs
+        let next = run.step(
+            RenameStage::CheckingParent { inode: inode() },
+            ResultKind::ReadOk,
+            &parent,
+        );
+        assert_eq!(next.as_deref(), Some("Storage read failed"));
+        let next = run.step(
+            RenameStage::ReadingContent { inode: inode() },
+            ResultKind::ReadOk,
+            b"hi",
+        );
+        assert_eq!(next.as_deref(), Some("Storage write failed"));
+        // Then the inode, then the old inode and content go
+        let next = run.step(
+            RenameStage::WritingContent { inode: inode() },
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage write failed"));
+        let next = run.step(
+            RenameStage::WritingInode { is_file: true },
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage delete failed"));
+        let next = run.step(
+            RenameStage::DeletingSourceInode { is_file: true },
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage delete failed"));
+        assert!(run.response().is_none());
+
+        let next = run.step(RenameStage::DeletingSourceContent, ResultKind::WriteOk, &[]);
+        assert_eq!(next, None);
+        assert!(matches!(run.response(), Some(Ok(()))));
+    }
+
+    #[test]
+    fn test_rename_onto_existing_target_fails() {
+        use crate::services::vfs::RenameStage;
+        use zos_ipc::storage::ResultKind;
+
+        let mut run = RenameRun::new("/home/a.txt", "/tmp/b.txt");
+        let target = serde_json::to_vec(&file_inode("/tmp/b.txt")).unwrap();
+        let next = run.step(
+            RenameStage::CheckingTarget {
+                inode: file_inode("/home/a.txt"),
+            },
+            ResultKind::ReadOk,
+            &target,
+        );
+        assert_eq!(next, None);
+        assert!(matches!(
+            run.response(),
+            Some(Err(zos_vfs::VfsError::AlreadyExists))
+        ));
+    }
+
+    #[test]
+    fn test_rename_into_missing_parent_fails() {
+        use crate::services::vfs::RenameStage;
+        use zos_ipc::storage::ResultKind;
+
+        let mut run = RenameRun::new("/home/a.txt", "/tmp/missing/b.txt");
+        let next = run.step(
+            RenameStage::CheckingParent {
+                inode: file_inode("/home/a.txt"),
+            },
+            ResultKind::NotFound,
+            &[],
+        );
+        assert_eq!(next, None);
+        assert!(matches!(
+            run.response(),
+            Some(Err(zos_vfs::VfsError::NotFound))
+        ));
+    }
+
+    #[test]
+    fn test_rename_moves_directory_subtree() {
+        use crate::services::vfs::{MoveWalk, MovedEntry, RenameStage, SubtreeStage};
+        use zos_ipc::storage::ResultKind;
+
+        let mut run = RenameRun::new("/home/dir", "/tmp/dir");
+        let source = serde_json::to_vec(&dir_inode("/home/dir")).unwrap();
+        let moving = |walk: MoveWalk, step| RenameStage::MovingSubtree { walk, step };
+
+        // A directory goes on to check the target like a file
+        let next = run.step(RenameStage::ReadingSource, ResultKind::ReadOk, &source);
+        assert_eq!(next.as_deref(), Some("Storage read failed"));
+
+        // Once its own inode is written, its children are listed
+        let next = run.step(
+            RenameStage::WritingInode { is_file: false },
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage list failed"));
+
+        // Each child is read
+        let next = run.step(
+            moving(
+                MoveWalk::default(),
+                SubtreeStage::Listing {
+                    dir: String::from("/home/dir"),
+                },
+            ),
+            ResultKind::ListOk,
+            br#"["/home/dir/a.txt","/home/dir/sub"]"#,
+        );
+        assert_eq!(next.as_deref(), Some("Storage read failed"));
+
+        // A file's content is copied under the new path, then its inode
+        let file = serde_json::to_vec(&file_inode("/home/dir/a.txt")).unwrap();
+        let next = run.step(
+            moving(
+                MoveWalk::default(),
+                SubtreeStage::ReadingInode {
+                    path: String::from("/home/dir/a.txt"),
+                },
+            ),
+            ResultKind::ReadOk,
+            &file,
+        );
+        assert_eq!(next.as_deref(), Some("Storage read failed"));
+        let next = run.step(
+            moving(
+                MoveWalk::default(),
+                SubtreeStage::ReadingContent {
+                    path: String::from("/home/dir/a.txt"),
+                    inode: file_inode("/home/dir/a.txt"),
+                },
+            ),
+            ResultKind::ReadOk,
+            b"hi",
+        );
+        assert_eq!(next.as_deref(), Some("Storage write failed"));
+
+        // A subdirectory's inode is written straight away, then listed
+        let sub = serde_json::to_vec(&dir_inode("/home/dir/sub")).unwrap();
+        let next = run.step(
+            moving(
+                MoveWalk::default(),
+                SubtreeStage::ReadingInode {
+                    path: String::from("/home/dir/sub"),
+                },
+            ),
+            ResultKind::ReadOk,
+            &sub,
+        );
+        assert_eq!(next.as_deref(), Some("Storage write failed"));
+        let next = run.step(
+            moving(
+                MoveWalk::default(),
+                SubtreeStage::WritingInode {
+                    path: String::from("/home/dir/sub"),
+                    is_file: false,
+                },
+            ),
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage list failed"));
+
+        // With everything copied, the old entries are deleted, then the
+        // directory's own inode, and the rename answers
+        let walk = MoveWalk {
+            copied: alloc::vec![MovedEntry {
+                path: String::from("/home/dir/a.txt"),
+                is_file: true,
+            }],
+            ..MoveWalk::default()
+        };
+        let next = run.step(
+            moving(
+                walk,
+                SubtreeStage::WritingInode {
+                    path: String::from("/home/dir/a.txt"),
+                    is_file: true,
+                },
+            ),
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage delete failed"));
+        let next = run.step(
+            moving(
+                MoveWalk::default(),
+                SubtreeStage::Deleting {
+                    entry: MovedEntry {
+                        path: String::from("/home/dir/a.txt"),
+                        is_file: true,
+                    },
+                    content: false,
+                },
+            ),
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage delete failed"));
+        assert!(run.response().is_none());
+        let next = run.step(
+            RenameStage::DeletingSourceInode { is_file: false },
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next, None);
+        assert!(matches!(run.response(), Some(Ok(()))));
+    }
+
+    #[test]
+    fn test_rename_subtree_failure_undoes_copies() {
+        use crate::services::vfs::{MoveWalk, MovedEntry, RenameStage, SubtreeStage};
+        use zos_ipc::storage::ResultKind;
+
+        let mut run = RenameRun::new("/home/dir", "/tmp/dir");
+        let walk = MoveWalk {
+            copied: alloc::vec![MovedEntry {
+                path: String::from("/home/dir/a.txt"),
+                is_file: true,
+            }],
+            ..MoveWalk::default()
+        };
+
+        // A failed copy takes back what was copied, starting with its inode
+        let next = run.step(
+            RenameStage::MovingSubtree {
+                walk,
+                step: SubtreeStage::ReadingInode {
+                    path: String::from("/home/dir/b.txt"),
+                },
+            },
+            ResultKind::Error,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage delete failed"));
+
+        let failed = || MoveWalk {
+            error: Some(zos_vfs::VfsError::StorageError(String::from("failed"))),
+            ..MoveWalk::default()
+        };
+        // Then the file's content
+        let next = run.step(
+            RenameStage::MovingSubtree {
+                walk: failed(),
+                step: SubtreeStage::Deleting {
+                    entry: MovedEntry {
+                        path: String::from("/home/dir/a.txt"),
+                        is_file: true,
+                    },
+                    content: false,
+                },
+            },
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage delete failed"));
+        // Then the directory's new inode
+        let next = run.step(
+            RenameStage::MovingSubtree {
+                walk: failed(),
+                step: SubtreeStage::Deleting {
+                    entry: MovedEntry {
+                        path: String::from("/home/dir/a.txt"),
+                        is_file: true,
+                    },
+                    content: true,
+                },
+            },
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next.as_deref(), Some("Storage delete failed"));
+        assert!(run.response().is_none());
+
+        // And the rename fails with what went wrong
+        let next = run.step(
+            RenameStage::MovingSubtree {
+                walk: failed(),
+                step: SubtreeStage::DeletingRoot,
+            },
+            ResultKind::WriteOk,
+            &[],
+        );
+        assert_eq!(next, None);
+        assert!(matches!(
+            run.response(),
+            Some(Err(zos_vfs::VfsError::StorageError(_)))
+        ));
+    }
+
+    #[test]
+    fn test_pending_op_rmdir_op() {
+        use crate::services::vfs::{RmdirStage, RmdirStep, RmdirWalk};
+        use zos_vfs::ipc::vfs_msg;
+
+        let mut service = VfsService::default();
+        let mut walk = RmdirWalk {
+            steps: alloc::vec![RmdirStep::Remove {
+                path: String::from("/tmp/dir"),
+                is_file: false,
+                charge: None,
+            }],
+            failed: Vec::new(),
+        };
+        walk.fail("/tmp/dir/locked", zos_vfs::VfsError::PermissionDenied);
+        assert_eq!(walk.failed[0].path, "/tmp/dir/locked");
+
+        let op = PendingOp::RmdirOp {
+            ctx: make_test_client_ctx(11),
+            path: String::from("/tmp/dir"),
+            perm_ctx: make_test_perm_ctx(),
+            recursive: true,
+            walk,
+            stage: RmdirStage::ListingChildren {
+                dir: String::from("/tmp/dir"),
+            },
+        };
+        assert_eq!(op.client_reply(), Some((11, vfs_msg::MSG_VFS_RMDIR_RESPONSE)));
+
+        service.pending_ops.insert(1, op);
+        assert!(service.has_inode_mutation_in_flight());
+    }
+
     #[test]
     fn test_pending_op_write_file_op() {
         use crate::services::vfs::WriteFileStage;
@@ -441,6 +1179,7 @@ mod tests {
                 stage: WriteFileStage::CheckingParent {
                     content: vec![1, 2, 3, 4],
                 },
+                reply: crate::services::vfs::WriteReply::Write,
             },
         );
         
@@ -459,4 +1198,1207 @@ mod tests {
             _ => panic!("expected WriteFileOp"),
         }
     }
+
+    #[test]
+    fn test_parse_inode_upgrades_legacy_layout() {
+        use crate::services::vfs::parse_inode;
+        use zos_vfs::{InodeType, INODE_SCHEMA_VERSION};
+
+        let legacy = br#"{"path":"/home/1/a.txt","parent_path":"/home/1","name":"a.txt",
+            "inode_type":"file","owner_id":1,"size":3,"created_at":1,"modified_at":2,
+            "content_hash":null,"encrypted":false,"symlink_target":null}"#;
+        let inode = parse_inode(legacy).expect("legacy inode should upgrade");
+        assert_eq!(inode.schema_version, INODE_SCHEMA_VERSION);
+        assert_eq!(inode.inode_type, InodeType::File);
+        assert_eq!(inode.owner_id, Some(1));
+        assert_eq!(inode.accessed_at, 2);
+
+        // Corrupt data still fails closed
+        assert!(parse_inode(b"not json").is_err());
+        assert!(parse_inode(br#"{"schema_version":99}"#).is_err());
+    }
+
+    #[test]
+    fn test_migration_sweep_defers_to_client_mutations() {
+        let mut service = VfsService::default();
+        assert!(!service.has_inode_mutation_in_flight());
+
+        // Reads do not block write-back
+        service.pending_ops.insert(
+            1,
+            PendingOp::GetInode {
+                ctx: make_test_client_ctx(10),
+                path: String::from("/tmp/a"),
+                op_type: InodeOpType::Stat,
+                perm_ctx: make_test_perm_ctx(),
+            },
+        );
+        service.pending_ops.insert(
+            2,
+            PendingOp::MigrateInode {
+                path: String::from("/tmp"),
+            },
+        );
+        assert!(!service.has_inode_mutation_in_flight());
+
+        service.pending_ops.insert(
+            3,
+            PendingOp::GetInode {
+                ctx: make_test_client_ctx(10),
+                path: String::from("/tmp/b"),
+                op_type: InodeOpType::Unlink,
+                perm_ctx: make_test_perm_ctx(),
+            },
+        );
+        assert!(service.has_inode_mutation_in_flight());
+    }
+
+    #[test]
+    fn test_migration_sweep_waits_when_storage_unavailable() {
+        let mut service = VfsService::default();
+        assert!(!service.migration.is_finished());
+
+        // Storage syscalls are unavailable off-target, so the root stays queued
+        service.start_migration_sweep();
+        service.pump_migration_sweep();
+        assert!(!service.migration.is_finished());
+        assert!(service.pending_ops.is_empty());
+    }
+
+    #[test]
+    fn test_checkpoint_tracks_interrupted_requests_and_sweep() {
+        use zos_vfs::ipc::vfs_msg;
+
+        let mut service = VfsService::default();
+        service.start_migration_sweep();
+        service.pending_ops.insert(
+            1,
+            PendingOp::GetInode {
+                ctx: make_test_client_ctx(10),
+                path: String::from("/tmp/a"),
+                op_type: InodeOpType::Stat,
+                perm_ctx: make_test_perm_ctx(),
+            },
+        );
+        service.pending_ops.insert(
+            2,
+            PendingOp::MigrateInode {
+                path: String::from("/tmp"),
+            },
+        );
+        // Intermediate steps have no client waiting
+        service.pending_ops.insert(
+            3,
+            PendingOp::PutInode {
+                ctx: None,
+                response_tag: vfs_msg::MSG_VFS_WRITE_RESPONSE,
+            },
+        );
+
+        // Nothing is tracked until the previous checkpoint has been read
+        service.pump_checkpoint(0);
+        assert!(!service.checkpoint.is_dirty());
+
+        service.checkpoint.restore(None);
+        service.pump_checkpoint(0);
+        let state = service.checkpoint.state();
+        assert_eq!(state.interrupted.len(), 1);
+        assert_eq!(state.interrupted[0].pid, 10);
+        assert_eq!(state.interrupted[0].response_tag, vfs_msg::MSG_VFS_STAT_RESPONSE);
+        // In-flight sweep paths are saved for re-reading
+        let progress = state.migration.as_ref().unwrap();
+        assert_eq!(progress.queue, ["/", "/tmp"]);
+    }
+
+    #[test]
+    fn test_restore_resumes_migration_sweep() {
+        use crate::services::vfs::handlers::checkpoint::VfsState;
+        use crate::services::vfs::handlers::migrate::SweepProgress;
+        use zos_apps::{StateStore, StatefulService};
+        use zos_ipc::storage::ResultKind;
+
+        let mut saved = StatefulService::<VfsState>::new("vfs", StateStore::Storage);
+        saved.restore(None);
+        saved.state_mut().migration = Some(SweepProgress {
+            target_version: zos_vfs::INODE_SCHEMA_VERSION,
+            content_format: zos_vfs::storage::encryption::CONTENT_FORMAT_VERSION,
+            queue: alloc::vec![String::from("/home")],
+            scanned: 5,
+            ..Default::default()
+        });
+        let data = saved.begin_checkpoint(0).unwrap();
+
+        let mut service = VfsService::default();
+        service
+            .handle_restore_state_result(ResultKind::ReadOk, &data)
+            .unwrap();
+        assert!(service.checkpoint.is_restored());
+        let progress = service.migration_progress().unwrap();
+        assert_eq!(progress.queue, ["/home"]);
+        assert_eq!(progress.scanned, 5);
+    }
+
+    #[test]
+    fn test_drain_waits_for_pending_ops() {
+        use zos_apps::{DrainPhase, Message};
+
+        let mut service = VfsService::default();
+        service.checkpoint.restore(None);
+        service.start_migration_sweep();
+        service.pending_ops.insert(
+            1,
+            PendingOp::GetInode {
+                ctx: make_test_client_ctx(10),
+                path: String::from("/tmp/a"),
+                op_type: InodeOpType::Stat,
+                perm_ctx: make_test_perm_ctx(),
+            },
+        );
+
+        // Only init may drain a service
+        let drain = |pid| Message::new(zos_process::init::MSG_SERVICE_DRAIN, pid, vec![], vec![]);
+        service.handle_drain(&drain(10)).unwrap();
+        assert!(!service.drain.is_draining());
+        service.handle_drain(&drain(1)).unwrap();
+        assert!(service.drain.is_draining());
+
+        // No new sweep batches, and no final checkpoint while a client op is pending
+        service.pump_migration_sweep();
+        service.pump_drain(0);
+        assert_eq!(service.pending_ops.len(), 1);
+        assert_eq!(service.checkpoint.sequence(), 0);
+
+        service.pending_ops.clear();
+        service.pump_drain(0);
+        assert_eq!(service.checkpoint.sequence(), 1);
+        // Storage is unavailable here, so the write failed and will be retried
+        assert_eq!(service.drain.phase(), DrainPhase::Checkpointing);
+        assert!(service.checkpoint.is_dirty());
+    }
+
+    #[test]
+    fn test_handles_are_per_process_and_limited() {
+        use crate::services::vfs::handlers::handles::{
+            HandleTable, OpenFile, MAX_HANDLES_PER_PROCESS,
+        };
+
+        let file = || OpenFile {
+            path: String::from("/tmp/log"),
+            writable: true,
+            perm_ctx: make_test_perm_ctx(),
+        };
+        let mut table = HandleTable::default();
+        let handle = table.open(10, file()).unwrap();
+        assert_ne!(handle, 0);
+
+        // Another process can't use or close the handle
+        assert!(table.get(11, handle).is_none());
+        assert!(table.close(11, handle).is_none());
+        assert_eq!(table.get(10, handle).unwrap().path, "/tmp/log");
+
+        for _ in 1..MAX_HANDLES_PER_PROCESS {
+            table.open(10, file()).unwrap();
+        }
+        assert!(table.open(10, file()).is_none());
+        assert!(table.open(11, file()).is_some());
+
+        assert!(table.close(10, handle).is_some());
+        assert!(table.get(10, handle).is_none());
+        assert!(table.open(10, file()).is_some());
+    }
+
+    #[test]
+    fn test_read_range_and_splice() {
+        use crate::services::vfs::handlers::handles::{read_range, splice_at, MAX_READ_AT_LEN};
+
+        let content = b"hello world";
+        assert_eq!(read_range(content, 6, 100), b"world");
+        assert_eq!(read_range(content, 0, 5), b"hello");
+        assert!(read_range(content, 11, 4).is_empty());
+        assert!(read_range(content, u64::MAX, u64::MAX).is_empty());
+        let big = vec![7u8; 3 * MAX_READ_AT_LEN as usize];
+        assert_eq!(read_range(&big, 1, u64::MAX).len(), MAX_READ_AT_LEN as usize);
+
+        assert_eq!(splice_at(content, 6, b"there"), b"hello there");
+        assert_eq!(splice_at(content, 9, b"LDS"), b"hello worLDS");
+        assert_eq!(splice_at(b"ab", 4, b"c"), b"ab\0\0c");
+    }
+
+    #[test]
+    fn test_write_reply_tags() {
+        use crate::services::vfs::WriteReply;
+        use zos_vfs::ipc::vfs_msg;
+
+        assert_eq!(WriteReply::Write.response_tag(), vfs_msg::MSG_VFS_WRITE_RESPONSE);
+        assert_eq!(
+            WriteReply::Open { writable: true }.response_tag(),
+            vfs_msg::MSG_VFS_OPEN_RESPONSE
+        );
+        assert_eq!(
+            WriteReply::WriteAt { size: 3 }.response_tag(),
+            vfs_msg::MSG_VFS_WRITE_AT_RESPONSE
+        );
+    }
+
+    #[test]
+    fn test_client_context_keeps_correlation_id() {
+        use crate::test_utils::mock_message;
+
+        // Kept for responses sent after storage results replaced it
+        let msg = mock_message(0x8000, 20, Vec::new()).with_correlation_id(Some(9));
+        let ctx = ClientContext::from_message(&msg);
+        assert_eq!(ctx.correlation_id, Some(9));
+        assert_eq!(ctx.pid, 20);
+        assert_eq!(
+            ClientContext::from_message(&mock_message(0x8000, 20, Vec::new())).correlation_id,
+            None
+        );
+    }
+
+    #[test]
+    fn test_watch_matching() {
+        use crate::services::vfs::handlers::watch::Watch;
+
+        let dir = Watch {
+            path: String::from("/home/u/docs"),
+            recursive: false,
+        };
+        assert!(dir.matches("/home/u/docs"));
+        assert!(dir.matches("/home/u/docs/a.txt"));
+        assert!(!dir.matches("/home/u/docs/sub/a.txt"));
+        assert!(!dir.matches("/home/u/docsx"));
+        assert!(!dir.matches("/home/u"));
+
+        let tree = Watch {
+            recursive: true,
+            ..dir
+        };
+        assert!(tree.matches("/home/u/docs/sub/a.txt"));
+        assert!(!tree.matches("/home/u/docsx/a.txt"));
+    }
+
+    #[test]
+    fn test_watches_are_per_process_and_limited() {
+        use crate::services::vfs::handlers::watch::{Watch, WatchTable, MAX_WATCHES_PER_PROCESS};
+
+        let watch = |path: &str| Watch {
+            path: String::from(path),
+            recursive: false,
+        };
+        let mut table = WatchTable::default();
+        let id = table.add(10, watch("/tmp")).unwrap();
+        assert_ne!(id, 0);
+        let other = table.add(11, watch("/tmp/a")).unwrap();
+
+        // A rename is reported to watches of either path, once each
+        assert_eq!(table.matching("/tmp/a", None), vec![(10, id), (11, other)]);
+        assert_eq!(table.matching("/var/x", Some("/tmp/x")), vec![(10, id)]);
+        assert!(table.matching("/var/x", None).is_empty());
+
+        // Another process can't remove the watch
+        assert!(table.remove(11, id).is_none());
+
+        for _ in 1..MAX_WATCHES_PER_PROCESS {
+            table.add(10, watch("/tmp")).unwrap();
+        }
+        assert!(table.add(10, watch("/tmp")).is_none());
+        assert!(table.remove(10, id).is_some());
+        assert!(table.add(10, watch("/tmp")).is_some());
+    }
+
+    #[test]
+    fn test_quota_reservations() {
+        use crate::services::vfs::handlers::quota::{Charge, QuotaTable};
+        use zos_vfs::storage::DEFAULT_QUOTA_BYTES;
+
+        let charge = |user_id, bytes| Some(Charge { user_id, bytes });
+        let mut table = QuotaTable::default();
+
+        let first = table.reserve(charge(1, 1000), None).unwrap();
+        assert_eq!(table.get(1).used_bytes, 1000);
+
+        // Overwriting only counts the growth, and a failed write is undone
+        let overwrite = table.reserve(charge(1, 1500), charge(1, 1000)).unwrap();
+        assert_eq!(table.get(1).used_bytes, 1500);
+        table.undo(&overwrite);
+        assert_eq!(table.get(1).used_bytes, 1000);
+
+        // Replacing another user's file moves the charge
+        table.reserve(charge(2, 400), charge(1, 1000)).unwrap();
+        assert_eq!(table.get(1).used_bytes, 0);
+        assert_eq!(table.get(2).used_bytes, 400);
+        table.undo(&first);
+        assert_eq!(table.get(1).used_bytes, 0);
+
+        // Growth past the limit is refused without touching the counters
+        match table.reserve(charge(2, DEFAULT_QUOTA_BYTES + 1), charge(2, 400)) {
+            Err(zos_vfs::VfsError::UserQuotaExceeded {
+                used,
+                limit,
+                requested,
+            }) => {
+                assert_eq!((used, limit), (400, DEFAULT_QUOTA_BYTES));
+                assert_eq!(requested, DEFAULT_QUOTA_BYTES + 1 - 400);
+            }
+            other => panic!("expected quota error, got {:?}", other),
+        }
+        assert_eq!(table.get(2).used_bytes, 400);
+        assert!(table.reserve(charge(2, DEFAULT_QUOTA_BYTES - 400), None).is_ok());
+        assert!(table.get(2).would_exceed(1));
+        // Shrinking is fine even at the limit
+        assert!(table.reserve(charge(2, 100), charge(2, 400)).is_ok());
+
+        table.release(charge(2, 100));
+        assert_eq!(table.get(2).used_bytes, DEFAULT_QUOTA_BYTES - 400);
+    }
+
+    #[test]
+    fn test_quota_counters_are_checkpointed() {
+        use crate::services::vfs::handlers::checkpoint::VfsState;
+        use crate::services::vfs::handlers::quota::Charge;
+        use zos_apps::{StateStore, StatefulService};
+        use zos_ipc::storage::ResultKind;
+
+        let user = u128::MAX - 1;
+        let mut saved = StatefulService::<VfsState>::new("vfs", StateStore::Storage);
+        saved.restore(None);
+        saved
+            .state_mut()
+            .quotas
+            .reserve(Some(Charge { user_id: user, bytes: 700 }), None)
+            .unwrap();
+        let data = saved.begin_checkpoint(0).unwrap();
+
+        // Writes served before the checkpoint is read are kept on top
+        let mut service = VfsService::default();
+        service
+            .quotas
+            .reserve(Some(Charge { user_id: user, bytes: 50 }), None)
+            .unwrap();
+        service
+            .handle_restore_state_result(ResultKind::ReadOk, &data)
+            .unwrap();
+        assert_eq!(service.quotas.get(user).used_bytes, 750);
+    }
+
+    #[test]
+    fn test_sealed_read_waits_for_content_key() {
+        use crate::test_utils::mock_message;
+        use zos_apps::AppContext;
+        use zos_ipc::keystore_svc;
+        use zos_ipc::storage::ResultKind;
+        use zos_vfs::client::keystore_async::{KeystoreError, KeystoreReadResponse};
+        use zos_vfs::storage::encryption::{self, ContentKey, SEAL_RANDOM_BYTES};
+
+        let user = u128::MAX - 7;
+        let key = ContentKey::from_bytes([3; 32]);
+        let blob = encryption::seal(&key, user, b"secret", &[9; SEAL_RANDOM_BYTES]).unwrap();
+        let read = PendingOp::GetContent {
+            ctx: make_test_client_ctx(20),
+            path: String::from("/users/1/notes.txt"),
+            perm_ctx: make_test_perm_ctx(),
+            expected_hash: None,
+        };
+
+        // Plaintext needs no key; sealed content waits for its owner's
+        let mut service = VfsService::default();
+        assert!(service
+            .park_for_content_key(read.clone(), ResultKind::ReadOk, b"legacy")
+            .is_some());
+        assert!(service
+            .park_for_content_key(read.clone(), ResultKind::ReadOk, &blob)
+            .is_none());
+        assert!(!service.content_keys.is_idle());
+        let replies: Vec<(u32, u32)> = service
+            .content_keys
+            .waiting_ops()
+            .filter_map(PendingOp::client_reply)
+            .collect();
+        assert_eq!(replies, vec![(20, zos_vfs::ipc::vfs_msg::MSG_VFS_READ_RESPONSE)]);
+
+        let stored = serde_json::to_vec(&key.to_stored(0)).unwrap();
+        let response = KeystoreReadResponse { result: Ok(stored) };
+        let msg = mock_message(
+            keystore_svc::MSG_KEYSTORE_READ_RESPONSE,
+            7,
+            serde_json::to_vec(&response).unwrap(),
+        );
+        let ctx = AppContext::new(4, 0, 0, None, None);
+        service.handle_keystore_response(&ctx, &msg).unwrap();
+        assert!(service.content_keys.is_idle());
+        assert_eq!(service.content_keys.open(&blob).unwrap(), b"secret");
+
+        // A user without a stored key gets a new one once it is written
+        let other = user - 1;
+        let other_blob = encryption::seal(&key, other, b"x", &[1; SEAL_RANDOM_BYTES]).unwrap();
+        assert!(service
+            .park_for_content_key(read, ResultKind::ReadOk, &other_blob)
+            .is_none());
+        let missing = KeystoreReadResponse {
+            result: Err(KeystoreError::NotFound),
+        };
+        let msg = mock_message(
+            keystore_svc::MSG_KEYSTORE_READ_RESPONSE,
+            7,
+            serde_json::to_vec(&missing).unwrap(),
+        );
+        service.handle_keystore_response(&ctx, &msg).unwrap();
+        assert!(!service.content_keys.has(other));
+        let msg = mock_message(
+            keystore_svc::MSG_KEYSTORE_WRITE_RESPONSE,
+            7,
+            br#"{"result":{"Ok":null}}"#.to_vec(),
+        );
+        service.handle_keystore_response(&ctx, &msg).unwrap();
+        assert!(service.content_keys.has(other));
+        assert!(service.content_keys.is_idle());
+        // The new key is not the one the blob was sealed with
+        assert!(service.content_keys.open(&other_blob).is_err());
+    }
+
+    #[test]
+    fn test_unowned_writes_need_no_content_key() {
+        use crate::services::vfs::{WriteFileStage, WriteReply};
+        use zos_ipc::storage::ResultKind;
+
+        let mut perm_ctx = make_test_perm_ctx();
+        let write = |perm_ctx: PermissionContext| PendingOp::WriteFileOp {
+            ctx: make_test_client_ctx(20),
+            path: String::from("/users/1/a.txt"),
+            perm_ctx,
+            stage: WriteFileStage::ReadingExisting {
+                content: vec![1, 2, 3],
+            },
+            reply: WriteReply::Write,
+        };
+
+        let mut service = VfsService::default();
+        assert!(service
+            .park_for_content_key(write(perm_ctx.clone()), ResultKind::NotFound, &[])
+            .is_some());
+        perm_ctx.user_id = Some(1);
+        assert!(service
+            .park_for_content_key(write(perm_ctx.clone()), ResultKind::NotFound, &[])
+            .is_none());
+        // A parked write still counts as a mutation in flight
+        assert!(service.has_inode_mutation_in_flight());
+    }
+
+    #[test]
+    fn test_sweep_restarts_for_new_content_format() {
+        use crate::services::vfs::handlers::migrate::{needs_sealing, SweepProgress};
+        use zos_vfs::schema::INODE_SCHEMA_VERSION;
+        use zos_vfs::Inode;
+
+        let mut file = Inode::new_file(
+            String::from("/users/1/a.txt"),
+            String::from("/users/1"),
+            String::from("a.txt"),
+            Some(1),
+            3,
+            None,
+            0,
+        );
+        assert!(needs_sealing(&file));
+        file.encrypted = true;
+        assert!(!needs_sealing(&file));
+        file.owner_id = None;
+        file.encrypted = false;
+        assert!(!needs_sealing(&file));
+
+        // A sweep finished before content was sealed runs again
+        let old: SweepProgress = serde_json::from_str(&format!(
+            r#"{{"target_version":{},"finished":true,"queue":[],"scanned":5,"upgraded":0,"deferred":0,"failed":0}}"#,
+            INODE_SCHEMA_VERSION
+        ))
+        .unwrap();
+        assert_eq!(old.content_format, 0);
+        let mut service = VfsService::default();
+        service.resume_migration_sweep(old);
+        let progress = service.migration_progress().unwrap();
+        assert!(!progress.finished);
+        assert_eq!(progress.scanned, 0);
+        assert_eq!(
+            progress.content_format,
+            zos_vfs::storage::encryption::CONTENT_FORMAT_VERSION
+        );
+    }
+
+    #[test]
+    fn test_chunk_patch_ranges() {
+        use crate::services::vfs::handlers::chunks::ChunkPatch;
+        use zos_vfs::storage::chunking::{ChunkManifest, CHUNK_SIZE};
+
+        let chunk = CHUNK_SIZE as u64;
+        let patch = |offset: u64, len: usize| ChunkPatch {
+            path: String::from("/tmp/big"),
+            perm_ctx: make_test_perm_ctx(),
+            offset,
+            data: vec![7; len],
+            old: ChunkManifest::new(1, 2 * chunk + 10),
+        };
+
+        // Inside the file: only the chunks the data covers
+        let inside = patch(chunk - 1, 2);
+        assert_eq!(inside.new_manifest().size, 2 * chunk + 10);
+        assert_eq!(inside.chunks(), 0..2);
+
+        // Past the end: the old last chunk is zero-filled up to the data
+        let past = patch(4 * chunk, 1);
+        assert_eq!(past.new_manifest().size, 4 * chunk + 1);
+        assert_eq!(past.new_manifest().id, 1);
+        assert_eq!(past.chunks(), 2..5);
+    }
+
+    #[test]
+    fn test_content_delete_bookkeeping() {
+        use crate::services::vfs::{ContentDeleteStage, UnlinkStage};
+        use alloc::boxed::Box;
+        use zos_vfs::ipc::vfs_msg;
+        use zos_vfs::storage::chunking::ChunkManifest;
+
+        let mut service = VfsService::default();
+        service.pending_ops.insert(
+            1,
+            PendingOp::ReleaseChunks {
+                manifest: ChunkManifest::new(1, 1 << 20),
+                next: 3,
+            },
+        );
+        assert_eq!(service.pending_ops[&1].client_reply(), None);
+        assert!(!service.has_inode_mutation_in_flight());
+
+        // Deleting content answers for, and counts as, the unlink it serves
+        service.pending_ops.insert(
+            2,
+            PendingOp::ContentDeleteOp {
+                path: String::from("/tmp/big"),
+                stage: ContentDeleteStage::ReadingRecord,
+                then: Box::new(PendingOp::UnlinkOp {
+                    ctx: make_test_client_ctx(30),
+                    path: String::from("/tmp/big"),
+                    perm_ctx: make_test_perm_ctx(),
+                    stage: UnlinkStage::DeletingContent { charge: None },
+                }),
+            },
+        );
+        assert_eq!(
+            service.pending_ops[&2].client_reply(),
+            Some((30, vfs_msg::MSG_VFS_UNLINK_RESPONSE))
+        );
+        assert!(service.has_inode_mutation_in_flight());
+    }
+
+    #[test]
+    fn test_link_bookkeeping() {
+        use crate::services::vfs::handlers::link::HeldContent;
+        use crate::services::vfs::{LinkStage, ReleaseStage};
+        use alloc::boxed::Box;
+        use zos_vfs::ipc::vfs_msg;
+        use zos_vfs::storage::blobs::{content_address, encode_link};
+        use zos_vfs::storage::chunking::ChunkManifest;
+
+        let hash = content_address(b"shared");
+        assert_eq!(
+            HeldContent::of(&encode_link(&hash)).unwrap(),
+            Some(HeldContent::Blob(hash))
+        );
+        let manifest = ChunkManifest::new(7, 1 << 20);
+        assert_eq!(
+            HeldContent::of(&manifest.encode()).unwrap(),
+            Some(HeldContent::Chunks(manifest))
+        );
+        assert_eq!(HeldContent::of(b"plain").unwrap(), None);
+
+        let mut service = VfsService::default();
+        service.pending_ops.insert(
+            1,
+            PendingOp::LinkOp {
+                ctx: make_test_client_ctx(30),
+                path: String::from("/tmp/a"),
+                link_path: String::from("/tmp/b"),
+                perm_ctx: make_test_perm_ctx(),
+                stage: LinkStage::WritingBatch {
+                    reservation: Default::default(),
+                    hash,
+                },
+            },
+        );
+        assert_eq!(
+            service.pending_ops[&1].client_reply(),
+            Some((30, vfs_msg::MSG_VFS_LINK_RESPONSE))
+        );
+        assert!(service.has_inode_mutation_in_flight());
+        assert!(service.blob_in_use(&hash));
+        assert!(!service.blob_in_use(&content_address(b"other")));
+
+        // A release waits while the link changes the count
+        service.release_held(HeldContent::Blob(hash));
+        assert_eq!(service.blob_releases, alloc::vec![hash]);
+        service.pending_ops.clear();
+
+        // Following a link answers for the read it serves
+        service.pending_ops.insert(
+            2,
+            PendingOp::FollowLink {
+                then: Box::new(PendingOp::ReadAtOp {
+                    ctx: make_test_client_ctx(31),
+                    offset: 0,
+                    length: 10,
+                    shm: None,
+                }),
+            },
+        );
+        service.pending_ops.insert(
+            3,
+            PendingOp::ReleaseBlob {
+                hash,
+                stage: ReleaseStage::ReadingCount,
+            },
+        );
+        assert_eq!(
+            service.pending_ops[&2].client_reply(),
+            Some((31, vfs_msg::MSG_VFS_READ_AT_RESPONSE))
+        );
+        assert_eq!(service.pending_ops[&3].client_reply(), None);
+        assert!(!service.has_inode_mutation_in_flight());
+        assert!(service.blob_in_use(&hash));
+    }
+
+    #[test]
+    fn test_mount_routing() {
+        use crate::services::vfs::handlers::mount::{route, MountSet, Route};
+        use zos_vfs::ipc::{vfs_msg, MountRequest};
+        use zos_vfs::{MountBackend, VfsError};
+
+        let request = |path: &str, backend, read_only| MountRequest {
+            path: String::from(path),
+            backend,
+            read_only,
+            files: Vec::new(),
+            size_limit: None,
+            remount: false,
+        };
+        let mut mounts = MountSet::default();
+        mounts
+            .mount(request("/tmp", MountBackend::Memory, false))
+            .unwrap();
+        mounts
+            .mount(request("/archive", MountBackend::Storage, true))
+            .unwrap();
+        assert!(mounts
+            .mount(request("/ro", MountBackend::Memory, true))
+            .is_err());
+
+        let local = route(&mounts, vfs_msg::MSG_VFS_WRITE, br#"{"path":"/tmp/a","content":[1,2],"encrypt":false}"#);
+        assert!(matches!(local, Route::Local(ref path) if path == "/tmp"));
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_READ, br#"{"path":"/home/1/a"}"#),
+            Route::Storage
+        ));
+
+        // Read-only storage mounts can be read but not changed
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_STAT, br#"{"path":"/archive/x"}"#),
+            Route::Storage
+        ));
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_UNLINK, br#"{"path":"/archive/x"}"#),
+            Route::Refuse(VfsError::PermissionDenied)
+        ));
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_OPEN, br#"{"path":"/archive/x","write":true,"create":false}"#),
+            Route::Refuse(VfsError::PermissionDenied)
+        ));
+
+        // Nothing moves between mounts, and handles stay on storage
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_RENAME, br#"{"from":"/tmp/a","to":"/home/a"}"#),
+            Route::Refuse(VfsError::NotSupported(_))
+        ));
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_OPEN, br#"{"path":"/tmp/a","write":false,"create":false}"#),
+            Route::Refuse(VfsError::NotSupported(_))
+        ));
+
+        // Invalid paths are left for the storage handlers to report
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_READ, br#"{"path":"/tmp/../etc"}"#),
+            Route::Storage
+        ));
+
+        mounts.umount("/tmp").unwrap();
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_READ, br#"{"path":"/tmp/a"}"#),
+            Route::Storage
+        ));
+    }
+
+    #[test]
+    fn test_tmp_mount_and_remount() {
+        use crate::services::vfs::handlers::mount::MountSet;
+        use zos_vfs::ipc::MountRequest;
+        use zos_vfs::{MountBackend, VfsError, DEFAULT_TMP_SIZE_LIMIT};
+
+        let mut mounts = MountSet::default();
+        mounts.mount_tmp().unwrap();
+        let tmp = mounts.resolve("/tmp/scratch");
+        assert_eq!(tmp.backend, MountBackend::Memory);
+        assert_eq!(tmp.size_limit, Some(DEFAULT_TMP_SIZE_LIMIT));
+
+        let remount = |path: &str, size_limit| MountRequest {
+            path: String::from(path),
+            backend: MountBackend::Memory,
+            read_only: false,
+            files: Vec::new(),
+            size_limit,
+            remount: true,
+        };
+        mounts.mount(remount("/tmp", Some(1024))).unwrap();
+        assert_eq!(mounts.resolve("/tmp").size_limit, Some(1024));
+        assert!(matches!(
+            mounts.mount(remount("/", None)),
+            Err(VfsError::InvalidRequest(_))
+        ));
+        assert!(matches!(
+            mounts.mount(remount("/scratch", None)),
+            Err(VfsError::NotFound)
+        ));
+
+        // Storage mounts have no size to limit
+        let mut storage = remount("/archive", Some(1024));
+        storage.backend = MountBackend::Storage;
+        storage.remount = false;
+        assert!(mounts.mount(storage).is_err());
+    }
+
+    #[test]
+    fn test_batch_table() {
+        use crate::services::vfs::handlers::batch::{BatchSlot, BatchTable, MAX_PENDING_BATCHES};
+        use zos_vfs::ipc::BatchEntry;
+
+        let mut table = BatchTable::default();
+        let id = table.start(make_test_client_ctx(10), 2, Some(1_000)).unwrap();
+        let slot = |index| BatchSlot {
+            id,
+            index,
+            response_tag: 0x8021,
+        };
+        let entry = |response: &str| BatchEntry {
+            tag: 0x8021,
+            response: String::from(response),
+        };
+
+        // Entries complete in any order; the batch answers once, in order
+        assert!(table.is_waiting(&slot(1)));
+        assert!(table.record(&slot(1), entry("b")).is_none());
+        assert!(!table.is_waiting(&slot(1)));
+        assert!(table.record(&slot(1), entry("again")).is_none());
+        let (ctx, entries) = table.record(&slot(0), entry("a")).unwrap();
+        assert_eq!(ctx.pid, 10);
+        let responses: Vec<&str> = entries.iter().map(|e| e.response.as_str()).collect();
+        assert_eq!(responses, vec!["a", "b"]);
+        assert!(table.is_empty());
+
+        // Late responses for a finished batch are dropped
+        assert!(table.record(&slot(0), entry("late")).is_none());
+
+        for _ in 0..MAX_PENDING_BATCHES {
+            table.start(make_test_client_ctx(11), 1, Some(1_000)).unwrap();
+        }
+        assert!(table.start(make_test_client_ctx(12), 1, None).is_err());
+        assert_eq!(table.expire(999), 0);
+        assert_eq!(table.expire(1_000), MAX_PENDING_BATCHES);
+
+        table.start(make_test_client_ctx(11), 1, None).unwrap();
+        table.start(make_test_client_ctx(12), 1, None).unwrap();
+        assert_eq!(table.remove_client(11), 1);
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_batch_answers_every_operation() {
+        use crate::services::vfs::handlers::batch::MAX_BATCH_OPS;
+        use zos_apps::{AppContext, Message};
+        use zos_vfs::ipc::vfs_msg;
+
+        let mut service = VfsService::default();
+        service.mounts.mount_tmp().unwrap();
+        let ctx = AppContext::new(4, 0, 0, None, None);
+        let batch = |data: &[u8]| Message {
+            tag: vfs_msg::MSG_VFS_BATCH,
+            from_pid: 20,
+            cap_slots: Vec::new(),
+            correlation_id: None,
+            data: data.to_vec(),
+        };
+
+        // Memory mount operations and refused paths answer at once, so the
+        // batch is answered before the handler returns
+        let ops = br#"{"ops":[
+            {"op":"mkdir","path":"/tmp/d","create_parents":false},
+            {"op":"write","path":"/tmp/d/f","content":[1,2,3],"encrypt":false},
+            {"op":"stat","path":"relative"},
+            {"op":"exists","path":"/tmp/missing"}
+        ]}"#;
+        service.handle_batch(&ctx, &batch(ops)).unwrap();
+        assert!(service.batches.borrow().is_empty());
+        assert!(service.batch_ctx.is_none());
+
+        let too_many = format!(
+            r#"{{"ops":[{}]}}"#,
+            vec![r#"{"op":"stat","path":"/tmp/a"}"#; MAX_BATCH_OPS + 1].join(",")
+        );
+        service.handle_batch(&ctx, &batch(too_many.as_bytes())).unwrap();
+        assert!(service.batches.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_batch_operations_reply_through_batch() {
+        use crate::services::vfs::handlers::batch::BatchSlot;
+        use zos_vfs::ipc::vfs_msg;
+
+        let mut ctx = make_test_client_ctx(20);
+        ctx.batch = Some(BatchSlot {
+            id: 1,
+            index: 0,
+            response_tag: vfs_msg::MSG_VFS_STAT_RESPONSE,
+        });
+        let op = |ctx: &ClientContext| PendingOp::GetInode {
+            ctx: ctx.clone(),
+            path: String::from("/home/a"),
+            op_type: InodeOpType::Stat,
+            perm_ctx: make_test_perm_ctx(),
+        };
+        assert_eq!(
+            op(&ctx).client_reply(),
+            Some((20, vfs_msg::MSG_VFS_BATCH_RESPONSE))
+        );
+        assert_eq!(
+            op(&make_test_client_ctx(20)).client_reply(),
+            Some((20, vfs_msg::MSG_VFS_STAT_RESPONSE))
+        );
+
+        // Cancelling the batch drops its operations and owes one response
+        let mut service = VfsService::default();
+        service
+            .batches
+            .get_mut()
+            .start(make_test_client_ctx(20), 1, None)
+            .unwrap();
+        service.pending_ops.insert(7, op(&ctx));
+        let replies = service.cancel_pending_ops(20, vfs_msg::MSG_VFS_BATCH);
+        assert!(service.pending_ops.is_empty());
+        assert!(service.batches.borrow().is_empty());
+        assert_eq!(
+            replies.into_iter().collect::<Vec<_>>(),
+            vec![(20, vfs_msg::MSG_VFS_BATCH_RESPONSE)]
+        );
+    }
+
+    #[test]
+    fn test_xattr_ops() {
+        use crate::services::vfs::handlers::mount::{route, MountSet, Route};
+        use crate::services::vfs::handlers::xattr::{XattrRequest, XattrStage};
+        use zos_vfs::ipc::vfs_msg;
+        use zos_vfs::VfsError;
+
+        let op = |request| PendingOp::XattrOp {
+            ctx: make_test_client_ctx(20),
+            path: String::from("/home/1/a"),
+            perm_ctx: make_test_perm_ctx(),
+            request,
+            stage: XattrStage::ReadingInode,
+        };
+        let set = XattrRequest::Set {
+            name: String::from("mime_type"),
+            value: Some(String::from("text/plain")),
+        };
+        assert_eq!(
+            op(set).client_reply(),
+            Some((20, vfs_msg::MSG_VFS_SETXATTR_RESPONSE))
+        );
+        assert_eq!(
+            op(XattrRequest::List).client_reply(),
+            Some((20, vfs_msg::MSG_VFS_LISTXATTR_RESPONSE))
+        );
+
+        // Attributes are kept in storage inodes only
+        let mut mounts = MountSet::default();
+        mounts.mount_tmp().unwrap();
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_GETXATTR, br#"{"path":"/tmp/a","name":"tags"}"#),
+            Route::Refuse(VfsError::NotSupported(_))
+        ));
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_LISTXATTR, br#"{"path":"/home/1/a"}"#),
+            Route::Storage
+        ));
+    }
+
+    #[test]
+    fn test_trash_ops() {
+        use crate::services::vfs::handlers::mount::{route, MountSet, Route};
+        use crate::services::vfs::handlers::trash::{TrashRequest, TrashScan, TrashStage};
+        use crate::services::vfs::{RenameReply, RenameStage};
+        use zos_vfs::ipc::vfs_msg;
+        use zos_vfs::VfsError;
+
+        // Moves into and out of the trash answer the requests behind them
+        let rename = |reply| PendingOp::RenameOp {
+            ctx: make_test_client_ctx(20),
+            from: String::from("/home/1/a"),
+            to: String::from("/home/1/.trash/5-0-a"),
+            perm_ctx: make_test_perm_ctx(),
+            stage: RenameStage::ReadingSource,
+            reply,
+        };
+        assert_eq!(
+            rename(RenameReply::Trash { deleted_at: 5 }).client_reply(),
+            Some((20, vfs_msg::MSG_VFS_UNLINK_RESPONSE))
+        );
+        assert_eq!(
+            rename(RenameReply::Restore).client_reply(),
+            Some((20, vfs_msg::MSG_VFS_TRASH_RESTORE_RESPONSE))
+        );
+
+        // The background sweep answers no one
+        let walk = |ctx, request| PendingOp::TrashOp {
+            ctx,
+            user_id: 1,
+            perm_ctx: make_test_perm_ctx(),
+            request,
+            scan: TrashScan::default(),
+            stage: TrashStage::ReadingDir,
+        };
+        assert_eq!(
+            walk(Some(make_test_client_ctx(20)), TrashRequest::List).client_reply(),
+            Some((20, vfs_msg::MSG_VFS_TRASH_LIST_RESPONSE))
+        );
+        assert_eq!(
+            walk(Some(make_test_client_ctx(20)), TrashRequest::Purge { id: None }).client_reply(),
+            Some((20, vfs_msg::MSG_VFS_TRASH_PURGE_RESPONSE))
+        );
+        assert_eq!(walk(None, TrashRequest::Expire { now: 5 }).client_reply(), None);
+
+        // Memory mounts delete outright and have no trash
+        let mut mounts = MountSet::default();
+        mounts.mount_tmp().unwrap();
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_UNLINK, br#"{"path":"/tmp/a","trash":true}"#),
+            Route::Refuse(VfsError::NotSupported(_))
+        ));
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_UNLINK, br#"{"path":"/tmp/a"}"#),
+            Route::Local(_)
+        ));
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_UNLINK, br#"{"path":"/home/1/a","trash":true}"#),
+            Route::Storage
+        ));
+    }
+
+    #[test]
+    fn test_trash_purges_directories() {
+        use crate::services::vfs::handlers::trash::{TrashRequest, TrashScan, TrashStage};
+        use crate::services::vfs::RmdirStep;
+        use zos_ipc::storage::ResultKind;
+        use zos_vfs::ipc::RmdirFailure;
+
+        let mut service = VfsService::default();
+        let mut step = |scan: TrashScan, stage, result_type, data: &[u8]| {
+            let result = service.handle_trash_op_result(
+                None,
+                1,
+                make_test_perm_ctx(),
+                TrashRequest::Purge { id: None },
+                scan,
+                stage,
+                result_type,
+                data,
+            );
+            match result {
+                Ok(()) => None,
+                Err(zos_apps::AppError::IpcError(step)) => {
+                    Some(String::from(step.split(':').next().unwrap_or_default()))
+                }
+                Err(e) => panic!("unexpected error: {:?}", e),
+            }
+        };
+
+        // A trashed directory is listed rather than deleted
+        let dir = serde_json::to_vec(&dir_inode("/home/1/.trash/5-0-docs")).unwrap();
+        let next = step(
+            TrashScan::default(),
+            TrashStage::ReadingEntry {
+                path: String::from("/home/1/.trash/5-0-docs"),
+            },
+            ResultKind::ReadOk,
+            &dir,
+        );
+        assert_eq!(next.as_deref(), Some("Storage list failed"));
+
+        // Its children are read, and a file's content goes first
+        let next = step(
+            TrashScan::default(),
+            TrashStage::ListingTree {
+                dir: String::from("/home/1/.trash/5-0-docs"),
+            },
+            ResultKind::ListOk,
+            br#"["/home/1/.trash/5-0-docs/a.txt"]"#,
+        );
+        assert_eq!(next.as_deref(), Some("Storage read failed"));
+        let file = serde_json::to_vec(&file_inode("/home/1/.trash/5-0-docs/a.txt")).unwrap();
+        let next = step(
+            TrashScan::default(),
+            TrashStage::ReadingTreeEntry {
+                path: String::from("/home/1/.trash/5-0-docs/a.txt"),
+            },
+            ResultKind::ReadOk,
+            &file,
+        );
+        assert_eq!(next.as_deref(), Some("Storage read failed"));
+
+        // Once its children are gone, the directory goes
+        let mut scan = TrashScan::default();
+        scan.tree.steps.push(RmdirStep::Remove {
+            path: String::from("/home/1/.trash/5-0-docs"),
+            is_file: false,
+            charge: None,
+        });
+        let next = step(scan.clone(), TrashStage::Listing, ResultKind::ListOk, b"[]");
+        assert_eq!(next.as_deref(), Some("Storage delete failed"));
+
+        // Unless one of them is still there
+        scan.tree.failed.push(RmdirFailure {
+            path: String::from("/home/1/.trash/5-0-docs/a.txt"),
+            error: zos_vfs::VfsError::PermissionDenied,
+        });
+        let next = step(scan, TrashStage::Listing, ResultKind::ListOk, b"[]");
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn test_snapshot_ops() {
+        use crate::services::vfs::handlers::mount::{changed_paths, route, MountSet, Route};
+        use crate::services::vfs::handlers::snapshot::{
+            SnapshotJob, SnapshotRequest, SnapshotStage,
+        };
+        use alloc::boxed::Box;
+        use zos_vfs::ipc::vfs_msg;
+        use zos_vfs::snapshot::SnapshotManifest;
+        use zos_vfs::VfsError;
+
+        let job = |request| {
+            let mut job = SnapshotJob::new(1, make_test_perm_ctx(), request);
+            job.manifest = Some(SnapshotManifest::new(
+                String::from("5-0"),
+                String::from("/home/1/docs"),
+                None,
+                5,
+            ));
+            job
+        };
+        let op = |request| PendingOp::SnapshotOp {
+            ctx: make_test_client_ctx(20),
+            job: Box::new(job(request)),
+            stage: SnapshotStage::ReadingIndex,
+        };
+        assert_eq!(
+            op(SnapshotRequest::Create).client_reply(),
+            Some((20, vfs_msg::MSG_VFS_SNAPSHOT_CREATE_RESPONSE))
+        );
+        assert_eq!(
+            op(SnapshotRequest::Delete { id: String::from("5-0") }).client_reply(),
+            Some((20, vfs_msg::MSG_VFS_SNAPSHOT_DELETE_RESPONSE))
+        );
+
+        // Only creates and restores hold their tree still
+        assert_eq!(job(SnapshotRequest::Create).frozen_root(), Some("/home/1/docs"));
+        let restore = SnapshotRequest::Restore { id: String::from("5-0") };
+        assert_eq!(job(restore).frozen_root(), Some("/home/1/docs"));
+        assert_eq!(job(SnapshotRequest::List).frozen_root(), None);
+
+        // Only requests that change something are held back
+        assert_eq!(
+            changed_paths(vfs_msg::MSG_VFS_RENAME, br#"{"from":"/home/1/a","to":"/home/1/docs/a"}"#),
+            ["/home/1/a", "/home/1/docs/a"]
+        );
+        assert!(changed_paths(vfs_msg::MSG_VFS_READ, br#"{"path":"/home/1/docs/a"}"#).is_empty());
+        assert!(changed_paths(vfs_msg::MSG_VFS_OPEN, br#"{"path":"/home/1/docs/a"}"#).is_empty());
+
+        // Snapshots are of storage trees only
+        let mut mounts = MountSet::default();
+        mounts.mount_tmp().unwrap();
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_SNAPSHOT_CREATE, br#"{"path":"/tmp"}"#),
+            Route::Refuse(VfsError::NotSupported(_))
+        ));
+        assert!(matches!(
+            route(&mounts, vfs_msg::MSG_VFS_SNAPSHOT_CREATE, br#"{"path":"/home/1"}"#),
+            Route::Storage
+        ));
+    }
+
+    #[test]
+    fn test_gc_sweep() {
+        use crate::services::vfs::handlers::gc::{GcItem, GcStage, GC_FIRST_RUN_MS, GC_INTERVAL_MS};
+        use zos_process::ResultKind;
+        use zos_vfs::storage::blobs::{blob_key, content_address, refcount_key};
+
+        // Storage syscalls are unavailable off-target: the run finds nothing
+        let mut service = VfsService::default();
+        service.pump_gc(0);
+        assert!(!service.gc.is_running());
+        service.pump_gc(GC_FIRST_RUN_MS);
+        assert!(!service.gc.is_running());
+        service.pump_gc(GC_FIRST_RUN_MS + GC_INTERVAL_MS - 1);
+        assert!(!service.gc.is_running());
+
+        // Listings queue content paths and blobs, nothing else
+        let hash = content_address(b"shared");
+        service.start_gc(0);
+        let keys = alloc::vec![
+            String::from("content:/home/1/a"),
+            String::from("inode:/home/1/a"),
+            blob_key(&hash),
+            refcount_key(&hash),
+            String::from("blob:not-hex"),
+        ];
+        let listing = serde_json::to_vec(&keys).unwrap();
+        service.handle_gc_result(GcStage::ListingContent, ResultKind::ListOk, &listing).unwrap();
+        service.handle_gc_result(GcStage::ListingBlobs, ResultKind::ListOk, &listing).unwrap();
+        assert_eq!(
+            service.gc.queued(),
+            [GcItem::Content(String::from("/home/1/a")), GcItem::Blob(hash)]
+        );
+
+        // A record whose inode exists, or may yet, is left alone
+        let checking = || GcStage::CheckingInode { path: String::from("/home/1/a") };
+        service.handle_gc_result(checking(), ResultKind::ExistsOk, &[1]).unwrap();
+        service.pending_ops.insert(
+            1,
+            PendingOp::GetInode {
+                ctx: make_test_client_ctx(10),
+                path: String::from("/home/1/b"),
+                op_type: InodeOpType::Unlink,
+                perm_ctx: make_test_perm_ctx(),
+            },
+        );
+        service.handle_gc_result(checking(), ResultKind::NotFound, &[]).unwrap();
+        assert_eq!(service.pending_ops.len(), 1);
+
+        // Deleted orphans are counted once
+        let deleted_content = GcStage::DeletingContent {
+            path: String::from("/home/1/a"),
+            bytes: 10,
+            held: None,
+        };
+        service.handle_gc_result(deleted_content, ResultKind::WriteOk, &[]).unwrap();
+        let deleted_blob = GcStage::DeletingBlob { hash, bytes: 32, manifest: None };
+        service.handle_gc_result(deleted_blob, ResultKind::WriteOk, &[]).unwrap();
+        assert_eq!(service.stats.reclaimed_records, 2);
+        assert_eq!(service.stats.reclaimed_bytes, 42);
+        assert!(service.stats_dirty);
+    }
 }
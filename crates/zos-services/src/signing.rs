@@ -0,0 +1,433 @@
+//! Signed inter-service requests
+//!
+//! Endpoint capabilities decide who can *reach* a service. For privileged
+//! operations, such as PermissionService asking VfsService to delete an
+//! app's data, the callee also checks who *ordered* the operation: the
+//! caller signs a canonical form of the request with its service key, and
+//! the callee verifies it against the key pinned for that service before
+//! acting.
+//!
+//! - Service keys are Ed25519 seeds held in KeystoreService under
+//!   [`ServiceKey::storage_path`]; only the public half leaves the caller.
+//! - Callees pin the first key a service registers ([`TrustedServiceKeys`]).
+//! - Each accepted request yields a [`SignedRequestAudit`] record that
+//!   carries the signature and public key, so the log can be re-verified
+//!   offline without the original payload.
+//!
+//! The signature covers:
+//! domain || len(caller) || caller || len(operation) || operation ||
+//! issued_at || nonce || SHA-256(payload)
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::services::identity::utils::{bytes_to_hex, hex_to_bytes};
+
+/// Domain separator prepended to the signed message.
+const REQUEST_DOMAIN: &[u8] = b"zos-signed-request-v1";
+
+/// How far `issued_at` may be from the callee's clock (30 seconds).
+pub const MAX_CLOCK_SKEW_MS: u64 = 30_000;
+
+/// Upper bound on pinned service keys (DoS protection per Rule 11).
+pub const MAX_TRUSTED_SERVICES: usize = 16;
+
+/// Debug-log prefix of audit records.
+pub const AUDIT_LOG_PREFIX: &str = "SIGNED_REQUEST:";
+
+/// A privileged request together with the caller's signature.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedRequest {
+    /// Service that ordered the operation (e.g. "permission")
+    pub caller: String,
+    /// Operation name, as understood by the callee (e.g. "rmdir")
+    pub operation: String,
+    /// Inner request JSON, exactly as the callee will parse it
+    pub payload: String,
+    /// When the request was signed (Unix timestamp ms)
+    pub issued_at: u64,
+    /// Strictly increasing per caller; rejects replays
+    pub nonce: u64,
+    /// Hex-encoded Ed25519 signature over the canonical message
+    pub signature: String,
+}
+
+impl SignedRequest {
+    /// SHA-256 of the payload, as covered by the signature.
+    pub fn payload_digest(&self) -> [u8; 32] {
+        Sha256::digest(self.payload.as_bytes()).into()
+    }
+}
+
+/// Canonical bytes covered by a request signature.
+fn signing_message(
+    caller: &str,
+    operation: &str,
+    issued_at: u64,
+    nonce: u64,
+    payload_digest: &[u8; 32],
+) -> Vec<u8> {
+    let mut message =
+        Vec::with_capacity(REQUEST_DOMAIN.len() + 4 + caller.len() + operation.len() + 16 + 32);
+    message.extend_from_slice(REQUEST_DOMAIN);
+    message.extend_from_slice(&(caller.len() as u16).to_be_bytes());
+    message.extend_from_slice(caller.as_bytes());
+    message.extend_from_slice(&(operation.len() as u16).to_be_bytes());
+    message.extend_from_slice(operation.as_bytes());
+    message.extend_from_slice(&issued_at.to_be_bytes());
+    message.extend_from_slice(&nonce.to_be_bytes());
+    message.extend_from_slice(payload_digest);
+    message
+}
+
+fn verify_signature(
+    public_key: &[u8; 32],
+    message: &[u8],
+    signature_hex: &str,
+) -> Result<(), SignedRequestError> {
+    let key = VerifyingKey::from_bytes(public_key).map_err(|_| SignedRequestError::BadSignature)?;
+    let sig_bytes: [u8; 64] = hex_to_bytes(signature_hex)
+        .and_then(|b| b.try_into().ok())
+        .ok_or(SignedRequestError::BadSignature)?;
+    key.verify(message, &Signature::from_bytes(&sig_bytes))
+        .map_err(|_| SignedRequestError::BadSignature)
+}
+
+/// Errors from registering or verifying service keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignedRequestError {
+    /// No key is pinned for the calling service
+    UnknownCaller(String),
+    /// The service already pinned a different key
+    KeyMismatch(String),
+    /// The pinned key table is full
+    TooManyServices,
+    /// `issued_at` is outside [`MAX_CLOCK_SKEW_MS`] of the callee's clock
+    Stale,
+    /// The nonce was not greater than the last accepted one
+    Replayed,
+    /// Signature is malformed or does not verify
+    BadSignature,
+}
+
+impl SignedRequestError {
+    /// Human-readable error message.
+    pub fn message(&self) -> String {
+        match self {
+            SignedRequestError::UnknownCaller(service) => {
+                format!("No signing key registered for service {}", service)
+            }
+            SignedRequestError::KeyMismatch(service) => {
+                format!("Service {} already registered a different key", service)
+            }
+            SignedRequestError::TooManyServices => String::from("Too many service keys"),
+            SignedRequestError::Stale => String::from("Signed request timestamp out of range"),
+            SignedRequestError::Replayed => String::from("Signed request nonce already used"),
+            SignedRequestError::BadSignature => {
+                String::from("Signed request signature verification failed")
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Caller side
+// =============================================================================
+
+/// A service's signing key, as stored in KeystoreService.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ServiceKey {
+    /// Service the key belongs to
+    pub service: String,
+    /// Ed25519 secret seed
+    seed: [u8; 32],
+    /// When the key was created (Unix timestamp ms)
+    pub created_at: u64,
+}
+
+impl ServiceKey {
+    /// Keystore path of a service's key.
+    pub fn storage_path(service: &str) -> String {
+        format!("/keys/services/{}/signing_key.json", service)
+    }
+
+    /// Wrap a freshly generated seed.
+    pub fn from_seed(service: &str, seed: [u8; 32], created_at: u64) -> Self {
+        Self {
+            service: String::from(service),
+            seed,
+            created_at,
+        }
+    }
+
+    /// Public key to register with callees.
+    pub fn public_key(&self) -> [u8; 32] {
+        SigningKey::from_bytes(&self.seed)
+            .verifying_key()
+            .to_bytes()
+    }
+}
+
+/// Signs requests with a [`ServiceKey`], handing out increasing nonces.
+pub struct RequestSigner {
+    key: ServiceKey,
+    last_nonce: u64,
+}
+
+impl RequestSigner {
+    pub fn new(key: ServiceKey) -> Self {
+        Self { key, last_nonce: 0 }
+    }
+
+    /// The underlying key.
+    pub fn key(&self) -> &ServiceKey {
+        &self.key
+    }
+
+    /// Sign `payload` for `operation` at `now_ms`.
+    ///
+    /// Nonces start from the wall clock, so they keep increasing across
+    /// restarts of the calling service.
+    pub fn sign(&mut self, operation: &str, payload: String, now_ms: u64) -> SignedRequest {
+        let nonce = now_ms.max(self.last_nonce + 1);
+        self.last_nonce = nonce;
+
+        let digest: [u8; 32] = Sha256::digest(payload.as_bytes()).into();
+        let message = signing_message(&self.key.service, operation, now_ms, nonce, &digest);
+        let signature = SigningKey::from_bytes(&self.key.seed).sign(&message);
+        SignedRequest {
+            caller: self.key.service.clone(),
+            operation: String::from(operation),
+            payload,
+            issued_at: now_ms,
+            nonce,
+            signature: bytes_to_hex(&signature.to_bytes()),
+        }
+    }
+}
+
+// =============================================================================
+// Callee side
+// =============================================================================
+
+/// Public keys pinned by a callee, with replay state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustedServiceKeys {
+    services: BTreeMap<String, TrustedService>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct TrustedService {
+    public_key: [u8; 32],
+    last_nonce: u64,
+}
+
+impl TrustedServiceKeys {
+    /// Pin `public_key` for `service`.
+    ///
+    /// The first registration wins; re-registering the same key is a no-op,
+    /// so services can register on every start.
+    pub fn register(
+        &mut self,
+        service: &str,
+        public_key: [u8; 32],
+    ) -> Result<(), SignedRequestError> {
+        match self.services.get(service) {
+            Some(trusted) if trusted.public_key == public_key => Ok(()),
+            Some(_) => Err(SignedRequestError::KeyMismatch(String::from(service))),
+            None if self.services.len() >= MAX_TRUSTED_SERVICES => {
+                Err(SignedRequestError::TooManyServices)
+            }
+            None => {
+                self.services.insert(
+                    String::from(service),
+                    TrustedService {
+                        public_key,
+                        last_nonce: 0,
+                    },
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Adopt keys pinned by a previous run.
+    ///
+    /// Restored pins win over keys registered since start; non-conflicting
+    /// registrations are kept.
+    pub fn restore(&mut self, pinned: TrustedServiceKeys) {
+        let registered = core::mem::replace(&mut self.services, pinned.services);
+        for (service, trusted) in registered {
+            self.services.entry(service).or_insert(trusted);
+        }
+    }
+
+    /// Check `request` and record its nonce.
+    ///
+    /// Returns the audit record to log on success.
+    pub fn verify(
+        &mut self,
+        request: &SignedRequest,
+        now_ms: u64,
+    ) -> Result<SignedRequestAudit, SignedRequestError> {
+        let trusted = self
+            .services
+            .get_mut(&request.caller)
+            .ok_or_else(|| SignedRequestError::UnknownCaller(request.caller.clone()))?;
+        if now_ms.abs_diff(request.issued_at) > MAX_CLOCK_SKEW_MS {
+            return Err(SignedRequestError::Stale);
+        }
+        if request.nonce <= trusted.last_nonce {
+            return Err(SignedRequestError::Replayed);
+        }
+
+        let audit = SignedRequestAudit {
+            caller: request.caller.clone(),
+            operation: request.operation.clone(),
+            issued_at: request.issued_at,
+            nonce: request.nonce,
+            payload_sha256: bytes_to_hex(&request.payload_digest()),
+            public_key: bytes_to_hex(&trusted.public_key),
+            signature: request.signature.clone(),
+        };
+        audit.verify()?;
+        trusted.last_nonce = request.nonce;
+        Ok(audit)
+    }
+}
+
+/// Record of an accepted signed request.
+///
+/// Self-contained: [`SignedRequestAudit::verify`] re-checks the signature
+/// from the record alone.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedRequestAudit {
+    pub caller: String,
+    pub operation: String,
+    pub issued_at: u64,
+    pub nonce: u64,
+    /// Hex SHA-256 of the request payload
+    pub payload_sha256: String,
+    /// Hex public key the signature was checked against
+    pub public_key: String,
+    /// Hex signature
+    pub signature: String,
+}
+
+impl SignedRequestAudit {
+    /// Re-verify the signature recorded here.
+    pub fn verify(&self) -> Result<(), SignedRequestError> {
+        let public_key: [u8; 32] = hex_to_bytes(&self.public_key)
+            .and_then(|b| b.try_into().ok())
+            .ok_or(SignedRequestError::BadSignature)?;
+        let digest: [u8; 32] = hex_to_bytes(&self.payload_sha256)
+            .and_then(|b| b.try_into().ok())
+            .ok_or(SignedRequestError::BadSignature)?;
+        let message = signing_message(
+            &self.caller,
+            &self.operation,
+            self.issued_at,
+            self.nonce,
+            &digest,
+        );
+        verify_signature(&public_key, &message, &self.signature)
+    }
+
+    /// Debug-log line for this record (`SIGNED_REQUEST:{json}`).
+    pub fn log_line(&self) -> String {
+        format!(
+            "{}{}",
+            AUDIT_LOG_PREFIX,
+            serde_json::to_string(self).unwrap_or_default()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000_000;
+
+    fn signer() -> RequestSigner {
+        RequestSigner::new(ServiceKey::from_seed("permission", [7u8; 32], NOW))
+    }
+
+    fn trusting(signer: &RequestSigner) -> TrustedServiceKeys {
+        let mut keys = TrustedServiceKeys::default();
+        keys.register("permission", signer.key().public_key())
+            .unwrap();
+        keys
+    }
+
+    #[test]
+    fn test_signed_request_verifies_and_audits() {
+        let mut signer = signer();
+        let mut keys = trusting(&signer);
+        let request = signer.sign("rmdir", String::from(r#"{"path":"/a"}"#), NOW);
+
+        let audit = keys.verify(&request, NOW + 1_000).unwrap();
+        assert_eq!(audit.caller, "permission");
+        assert_eq!(audit.operation, "rmdir");
+        assert_eq!(audit.verify(), Ok(()));
+        assert!(audit.log_line().starts_with(AUDIT_LOG_PREFIX));
+
+        let mut forged = audit.clone();
+        forged.operation = String::from("unlink");
+        assert_eq!(forged.verify(), Err(SignedRequestError::BadSignature));
+    }
+
+    #[test]
+    fn test_rejects_tampered_replayed_and_stale() {
+        let mut signer = signer();
+        let mut keys = trusting(&signer);
+
+        let mut tampered = signer.sign("rmdir", String::from(r#"{"path":"/a"}"#), NOW);
+        tampered.payload = String::from(r#"{"path":"/"}"#);
+        assert_eq!(
+            keys.verify(&tampered, NOW),
+            Err(SignedRequestError::BadSignature)
+        );
+
+        let request = signer.sign("rmdir", String::from(r#"{"path":"/a"}"#), NOW);
+        assert!(keys.verify(&request, NOW).is_ok());
+        assert_eq!(
+            keys.verify(&request, NOW),
+            Err(SignedRequestError::Replayed)
+        );
+
+        let late = signer.sign("rmdir", String::from(r#"{"path":"/b"}"#), NOW);
+        assert_eq!(
+            keys.verify(&late, NOW + MAX_CLOCK_SKEW_MS + 1),
+            Err(SignedRequestError::Stale)
+        );
+    }
+
+    #[test]
+    fn test_first_registered_key_is_pinned() {
+        let signer = signer();
+        let mut keys = trusting(&signer);
+        assert_eq!(
+            keys.register("permission", signer.key().public_key()),
+            Ok(())
+        );
+
+        let other = ServiceKey::from_seed("permission", [9u8; 32], NOW).public_key();
+        assert_eq!(
+            keys.register("permission", other),
+            Err(SignedRequestError::KeyMismatch(String::from("permission")))
+        );
+
+        let mut impostor = RequestSigner::new(ServiceKey::from_seed("identity", [9u8; 32], NOW));
+        let request = impostor.sign("rmdir", String::new(), NOW);
+        assert_eq!(
+            keys.verify(&request, NOW),
+            Err(SignedRequestError::UnknownCaller(String::from("identity")))
+        );
+    }
+}
@@ -14,6 +14,7 @@ pub fn mock_message(tag: u32, from_pid: u32, data: Vec<u8>) -> Message {
         tag,
         from_pid,
         cap_slots: Vec::new(),
+        correlation_id: None,
         data,
     }
 }
@@ -29,6 +30,7 @@ pub fn mock_message_with_caps(
         tag,
         from_pid,
         cap_slots,
+        correlation_id: None,
         data,
     }
 }
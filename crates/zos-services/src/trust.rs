@@ -0,0 +1,158 @@
+//! Caller trust by service name
+//!
+//! A caller's PID says little about what it is: boot services spawn as
+//! their dependencies become ready, and a restarted service comes back
+//! under a fresh PID. Services decide how far to trust a caller from the
+//! name it runs under in the kernel's process table instead, the name of
+//! the binary Init or the supervisor spawned, which the process can't
+//! choose. Only the supervisor (PID 0) and Init (PID 1) are known by PID.
+//!
+//! PIDs are never reused, so a name once looked up stays right and is
+//! cached in [`CallerNames`].
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::cell::RefCell;
+use zos_apps::syscall;
+use zos_process::{pid, services};
+
+/// Upper bound on cached names (Rule 11: resource limits). The cache is
+/// emptied when full; names are looked up again as needed.
+pub const MAX_CACHED_NAMES: usize = 256;
+
+/// What a caller is, for deciding how far to trust it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallerClass {
+    /// The supervisor (PID 0), which relays the web desktop's requests
+    Supervisor,
+    /// Init (PID 1)
+    Init,
+    /// A boot service (`zos_process::services::SYSTEM`)
+    SystemService,
+    /// The desktop shell process
+    Desktop,
+    /// Anything else, including processes that have exited
+    Application,
+}
+
+impl CallerClass {
+    /// Classify a caller from its PID and the name it runs under.
+    pub fn of(pid: u32, name: Option<&str>) -> Self {
+        match (pid, name) {
+            (pid::SUPERVISOR, _) => Self::Supervisor,
+            (pid::INIT, _) => Self::Init,
+            (_, Some(name)) if services::SYSTEM.contains(&name) => Self::SystemService,
+            (_, Some(services::DESKTOP)) => Self::Desktop,
+            _ => Self::Application,
+        }
+    }
+
+    /// Whether the caller acts for every user (supervisor, Init or a boot
+    /// service).
+    pub fn is_system(self) -> bool {
+        matches!(self, Self::Supervisor | Self::Init | Self::SystemService)
+    }
+}
+
+/// The name `pid` runs under, from the kernel's process table.
+pub fn process_name(pid: u32) -> Option<String> {
+    syscall::list_processes()
+        .into_iter()
+        .find(|p| p.pid == pid)
+        .map(|p| p.name)
+}
+
+/// Process names by PID, looked up on first use.
+#[derive(Debug, Default)]
+pub struct CallerNames {
+    names: RefCell<BTreeMap<u32, String>>,
+}
+
+impl CallerNames {
+    /// The name `pid` runs under, or None if it isn't running.
+    pub fn name_of(&self, pid: u32) -> Option<String> {
+        if let Some(name) = self.names.borrow().get(&pid) {
+            return Some(name.clone());
+        }
+        let name = process_name(pid)?;
+        self.remember(pid, &name);
+        Some(name)
+    }
+
+    /// Whether `pid` runs under `name`.
+    pub fn is(&self, pid: u32, name: &str) -> bool {
+        self.name_of(pid).as_deref() == Some(name)
+    }
+
+    /// Classify `pid`; the supervisor and Init need no lookup.
+    pub fn class_of(&self, pid: u32) -> CallerClass {
+        match pid {
+            pid::SUPERVISOR | pid::INIT => CallerClass::of(pid, None),
+            _ => CallerClass::of(pid, self.name_of(pid).as_deref()),
+        }
+    }
+
+    /// Record the name `pid` runs under.
+    pub fn remember(&self, pid: u32, name: &str) {
+        let mut names = self.names.borrow_mut();
+        if names.len() >= MAX_CACHED_NAMES && !names.contains_key(&pid) {
+            names.clear();
+        }
+        names.insert(pid, String::from(name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_class_follows_name_not_pid() {
+        // The event bus spawned 9th is PID 10 - still just a service
+        assert_eq!(
+            CallerClass::of(10, Some("events")),
+            CallerClass::SystemService
+        );
+        assert_eq!(CallerClass::of(25, Some("vfs")), CallerClass::SystemService);
+        assert_eq!(
+            CallerClass::of(3, Some("terminal")),
+            CallerClass::Application
+        );
+        assert_eq!(CallerClass::of(12, Some("desktop")), CallerClass::Desktop);
+        assert_eq!(CallerClass::of(12, None), CallerClass::Application);
+        assert_eq!(CallerClass::of(0, None), CallerClass::Supervisor);
+        assert_eq!(CallerClass::of(1, Some("vfs")), CallerClass::Init);
+    }
+
+    #[test]
+    fn test_is_system() {
+        assert!(CallerClass::Supervisor.is_system());
+        assert!(CallerClass::Init.is_system());
+        assert!(CallerClass::SystemService.is_system());
+        assert!(!CallerClass::Desktop.is_system());
+        assert!(!CallerClass::Application.is_system());
+    }
+
+    #[test]
+    fn test_cached_names() {
+        let names = CallerNames::default();
+        // Not running: no process table outside the kernel
+        assert_eq!(names.name_of(10), None);
+        assert_eq!(names.class_of(10), CallerClass::Application);
+
+        names.remember(10, "events");
+        assert!(names.is(10, "events"));
+        assert!(!names.is(10, "desktop"));
+        assert_eq!(names.class_of(10), CallerClass::SystemService);
+        assert_eq!(names.class_of(1), CallerClass::Init);
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let names = CallerNames::default();
+        for pid in 0..MAX_CACHED_NAMES as u32 + 1 {
+            names.remember(pid + 2, "app");
+        }
+        assert_eq!(names.names.borrow().len(), 1);
+    }
+}
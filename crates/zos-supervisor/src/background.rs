@@ -148,6 +148,21 @@ impl DesktopBackground {
         }
     }
 
+    /// Apply accessibility display settings (same JSON as the desktop controller)
+    /// Returns false if the JSON is invalid or the renderer isn't initialized
+    #[wasm_bindgen]
+    pub fn set_display_settings_json(&mut self, json: &str) -> bool {
+        let Ok(settings) = serde_json::from_str::<zos_desktop::DisplaySettings>(json) else {
+            return false;
+        };
+        if let Some(renderer) = &mut self.renderer {
+            renderer.set_display_settings(settings);
+            true
+        } else {
+            false
+        }
+    }
+
     /// Set workspace layout dimensions (must match Rust desktop engine)
     /// Called when workspaces are created or screen is resized
     #[wasm_bindgen]
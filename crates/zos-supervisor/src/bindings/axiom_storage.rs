@@ -86,6 +86,15 @@ pub(crate) fn commit_type_to_string(ct: &zos_kernel::CommitType) -> String {
             "ProcessFaulted(pid={}, reason={}, desc={})",
             pid, reason, description
         ),
+        zos_kernel::CommitType::ProcessPriorityChanged { pid, priority } => {
+            format!("ProcessPriorityChanged(pid={}, priority={})", pid, priority)
+        }
+        zos_kernel::CommitType::ProcessMemoryLimitChanged { pid, limit } => {
+            format!("ProcessMemoryLimitChanged(pid={}, limit={})", pid, limit)
+        }
+        zos_kernel::CommitType::ProcessGroupChanged { pid, pgid } => {
+            format!("ProcessGroupChanged(pid={}, pgid={})", pid, pgid)
+        }
         zos_kernel::CommitType::CapInserted {
             pid, slot, cap_id, ..
         } => format!("CapInserted(pid={}, slot={}, cap={})", pid, slot, cap_id),
@@ -117,6 +126,25 @@ pub(crate) fn commit_type_to_string(ct: &zos_kernel::CommitType) -> String {
             "MessageSent(from={}, ep={}, tag={}, size={})",
             from_pid, to_endpoint, tag, size
         ),
+        zos_kernel::CommitType::NotificationCreated { id, owner } => {
+            format!("NotificationCreated(id={}, owner={})", id, owner)
+        }
+        zos_kernel::CommitType::NotificationDestroyed { id } => {
+            format!("NotificationDestroyed(id={})", id)
+        }
+        zos_kernel::CommitType::NotificationSignaled { from_pid, id, bits } => {
+            format!(
+                "NotificationSignaled(from={}, id={}, bits={:#010x})",
+                from_pid, id, bits
+            )
+        }
+        zos_kernel::CommitType::FaultInjectionSeeded { seed, faults } => {
+            format!("FaultInjectionSeeded(seed={}, faults={:#04x})", seed, faults)
+        }
+        zos_kernel::CommitType::Batch { events } => {
+            let events: Vec<String> = events.iter().map(commit_type_to_string).collect();
+            format!("Batch[{}]", events.join(", "))
+        }
     }
 }
 
@@ -128,11 +156,19 @@ pub(crate) fn commit_type_short(ct: &zos_kernel::CommitType) -> &'static str {
         zos_kernel::CommitType::ProcessCreated { .. } => "ProcCreate",
         zos_kernel::CommitType::ProcessExited { .. } => "ProcExit",
         zos_kernel::CommitType::ProcessFaulted { .. } => "ProcFault",
+        zos_kernel::CommitType::ProcessPriorityChanged { .. } => "ProcPriority",
+        zos_kernel::CommitType::ProcessMemoryLimitChanged { .. } => "ProcMemLimit",
+        zos_kernel::CommitType::ProcessGroupChanged { .. } => "ProcGroup",
         zos_kernel::CommitType::CapInserted { .. } => "CapInsert",
         zos_kernel::CommitType::CapRemoved { .. } => "CapRemove",
         zos_kernel::CommitType::CapGranted { .. } => "CapGrant",
         zos_kernel::CommitType::EndpointCreated { .. } => "EpCreate",
         zos_kernel::CommitType::EndpointDestroyed { .. } => "EpDestroy",
         zos_kernel::CommitType::MessageSent { .. } => "MsgSent",
+        zos_kernel::CommitType::NotificationCreated { .. } => "NtfnCreate",
+        zos_kernel::CommitType::NotificationDestroyed { .. } => "NtfnDestroy",
+        zos_kernel::CommitType::NotificationSignaled { .. } => "NtfnSignal",
+        zos_kernel::CommitType::FaultInjectionSeeded { .. } => "ChaosSeed",
+        zos_kernel::CommitType::Batch { .. } => "Batch",
     }
 }
@@ -21,6 +21,12 @@
 
 use wasm_bindgen::prelude::*;
 
+/// Inode schema version written by bootstrap.
+///
+/// Must match `zos_vfs::schema::INODE_SCHEMA_VERSION`; older records are
+/// upgraded by the VFS service.
+const INODE_SCHEMA_VERSION: f64 = 2.0;
+
 #[wasm_bindgen]
 extern "C" {
     /// ZosStorage JavaScript object for IndexedDB persistence (bootstrap only)
@@ -51,6 +57,7 @@ pub(crate) fn create_root_inode() -> JsValue {
     let obj = js_sys::Object::new();
     let now = js_sys::Date::now();
 
+    let _ = js_sys::Reflect::set(&obj, &"schema_version".into(), &JsValue::from_f64(INODE_SCHEMA_VERSION));
     let _ = js_sys::Reflect::set(&obj, &"path".into(), &JsValue::from_str("/"));
     let _ = js_sys::Reflect::set(&obj, &"parent_path".into(), &JsValue::from_str(""));
     let _ = js_sys::Reflect::set(&obj, &"name".into(), &JsValue::from_str(""));
@@ -82,6 +89,7 @@ pub(crate) fn create_dir_inode(path: &str, parent_path: &str, name: &str) -> JsV
     let obj = js_sys::Object::new();
     let now = js_sys::Date::now();
 
+    let _ = js_sys::Reflect::set(&obj, &"schema_version".into(), &JsValue::from_f64(INODE_SCHEMA_VERSION));
     let _ = js_sys::Reflect::set(&obj, &"path".into(), &JsValue::from_str(path));
     let _ = js_sys::Reflect::set(&obj, &"parent_path".into(), &JsValue::from_str(parent_path));
     let _ = js_sys::Reflect::set(&obj, &"name".into(), &JsValue::from_str(name));
@@ -61,3 +61,13 @@ pub const SYS_EXIT: u32 = 0x11;
 
 /// SYS_IPC_RECEIVE syscall number - receive IPC message
 pub const SYS_IPC_RECEIVE: u32 = 0x41;
+
+/// SYS_SHM_CREATE syscall number - first of the shared memory syscalls
+pub const SYS_SHM_CREATE: u32 = 0x60;
+
+/// SYS_SHM_FLUSH syscall number - copy a mapping into its region
+pub const SYS_SHM_FLUSH: u32 = 0x63;
+
+/// SYS_SHM_FETCH syscall number - copy a region into a mapping (last of the
+/// shared memory syscalls)
+pub const SYS_SHM_FETCH: u32 = 0x64;
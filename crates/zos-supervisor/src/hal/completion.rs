@@ -0,0 +1,133 @@
+//! Async completion queue for WASM HAL
+//!
+//! ZosStorage, ZosKeystore and ZosNetwork report results one JavaScript
+//! callback at a time. Each is matched to its pending request here and
+//! queued; the supervisor drains the queue through
+//! `HAL::poll_async_completions` on its next tick, like the x86_64 kernel
+//! loop does.
+//!
+//! A request JavaScript never reports on can be failed from here instead
+//! (`do_fail_async`); the late report, if one ever comes, is then an orphan.
+//!
+//! # Safety Invariants
+//!
+//! ## Success Criteria
+//! - A completion is queued only for a request that is still pending, and
+//!   carries the PID recorded when that request started
+//!
+//! ## Acceptable Partial Failures
+//! - Unknown request_id: Logged, nothing queued (orphaned response)
+//!
+//! ## Forbidden States
+//! - A request completed twice (its PID is taken when it is queued)
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use zos_hal::{AsyncChannel, AsyncCompletion, AsyncOutcome, HalError, PendingAsyncOp};
+
+use super::{PendingRequest, WasmHal};
+use crate::util::log;
+
+impl WasmHal {
+    /// Queue the outcome of a finished request.
+    ///
+    /// Returns false, queueing nothing, if no such request is pending.
+    pub fn complete_async(
+        &self,
+        channel: AsyncChannel,
+        request_id: u32,
+        outcome: AsyncOutcome,
+    ) -> bool {
+        let pid = match channel {
+            AsyncChannel::Storage => self.do_take_storage_request_pid(request_id),
+            AsyncChannel::Keystore => self.do_take_keystore_request_pid(request_id),
+            AsyncChannel::Network => self.do_take_network_request_pid(request_id),
+        };
+        let Some(pid) = pid else {
+            log(&format!(
+                "[wasm-hal] ERROR: Unknown {:?} request_id {} (orphaned response)",
+                channel, request_id
+            ));
+            return false;
+        };
+
+        match self.completions.lock() {
+            Ok(mut completions) => {
+                completions.push(AsyncCompletion {
+                    channel,
+                    request_id,
+                    pid,
+                    outcome,
+                });
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// List the requests of every channel still waiting for JavaScript
+    pub fn do_pending_async_ops(&self) -> Vec<PendingAsyncOp> {
+        let mut ops = Vec::new();
+        for channel in [
+            AsyncChannel::Storage,
+            AsyncChannel::Keystore,
+            AsyncChannel::Network,
+        ] {
+            let Ok(pending) = self.pending_requests(channel).lock() else {
+                continue;
+            };
+            ops.extend(pending.iter().map(|(&request_id, request)| PendingAsyncOp {
+                channel,
+                request_id,
+                pid: request.pid,
+                submitted_at: request.submitted_at,
+            }));
+        }
+        ops
+    }
+
+    /// Complete a pending request with an error instead of its real outcome
+    pub fn do_fail_async(
+        &self,
+        channel: AsyncChannel,
+        request_id: u32,
+        reason: &str,
+    ) -> Result<(), HalError> {
+        let pending = self
+            .pending_requests(channel)
+            .lock()
+            .map(|pending| pending.contains_key(&request_id))
+            .unwrap_or(false);
+        if !pending {
+            return Err(HalError::NotFound);
+        }
+
+        log(&format!(
+            "[wasm-hal] Failing {:?} request_id {}: {}",
+            channel, request_id, reason
+        ));
+        let outcome = AsyncOutcome::Error(reason.to_string());
+        if self.complete_async(channel, request_id, outcome) {
+            Ok(())
+        } else {
+            Err(HalError::NotFound)
+        }
+    }
+
+    fn pending_requests(&self, channel: AsyncChannel) -> &Arc<Mutex<HashMap<u32, PendingRequest>>> {
+        match channel {
+            AsyncChannel::Storage => &self.pending_storage_requests,
+            AsyncChannel::Keystore => &self.pending_keystore_requests,
+            AsyncChannel::Network => &self.pending_network_requests,
+        }
+    }
+
+    /// Take every queued completion, oldest first
+    pub fn do_poll_async_completions(&self) -> Vec<AsyncCompletion> {
+        self.completions
+            .lock()
+            .map(|mut completions| core::mem::take(&mut *completions))
+            .unwrap_or_default()
+    }
+}
@@ -15,11 +15,15 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
-use zos_hal::{HalError, NetworkRequestId, StorageRequestId, HAL};
+use zos_hal::{
+    AsyncChannel, AsyncCompletion, HalError, NetworkRequestId, PendingAsyncOp, StorageRequestId,
+    HAL,
+};
 
 use crate::util::log;
 use crate::worker::{self, PendingSyscall, WasmProcessHandle, WorkerMessage, WorkerProcess};
 
+mod completion;
 mod network;
 mod process;
 mod storage;
@@ -36,6 +40,15 @@ const MAX_PENDING_NETWORK_REQUESTS: usize = 100;
 /// Key operations are similar to storage, use the same limit.
 const MAX_PENDING_KEYSTORE_REQUESTS: usize = 1000;
 
+/// A request waiting for JavaScript to report its outcome
+#[derive(Clone, Copy, Debug)]
+struct PendingRequest {
+    /// Process that made it
+    pid: u64,
+    /// `now_nanos` when it started
+    submitted_at: u64,
+}
+
 /// WASM HAL implementation
 ///
 /// This HAL runs in the browser and uses Web Workers for process isolation.
@@ -49,16 +62,18 @@ pub struct WasmHal {
     incoming_messages: Arc<Mutex<Vec<WorkerMessage>>>,
     /// Next storage request ID (monotonically increasing)
     next_storage_request_id: AtomicU32,
-    /// Pending storage requests: request_id -> requesting PID
-    pending_storage_requests: Arc<Mutex<HashMap<u32, u64>>>,
+    /// Pending storage requests by request_id
+    pending_storage_requests: Arc<Mutex<HashMap<u32, PendingRequest>>>,
     /// Next network request ID (monotonically increasing)
     next_network_request_id: AtomicU32,
-    /// Pending network requests: request_id -> requesting PID
-    pending_network_requests: Arc<Mutex<HashMap<u32, u64>>>,
+    /// Pending network requests by request_id
+    pending_network_requests: Arc<Mutex<HashMap<u32, PendingRequest>>>,
     /// Next keystore request ID (monotonically increasing)
     next_keystore_request_id: AtomicU32,
-    /// Pending keystore requests: request_id -> requesting PID
-    pending_keystore_requests: Arc<Mutex<HashMap<u32, u64>>>,
+    /// Pending keystore requests by request_id
+    pending_keystore_requests: Arc<Mutex<HashMap<u32, PendingRequest>>>,
+    /// Finished storage, keystore and network requests, not yet polled
+    completions: Arc<Mutex<Vec<AsyncCompletion>>>,
 }
 
 impl WasmHal {
@@ -74,6 +89,15 @@ impl WasmHal {
             pending_network_requests: Arc::new(Mutex::new(HashMap::new())),
             next_keystore_request_id: AtomicU32::new(1),
             pending_keystore_requests: Arc::new(Mutex::new(HashMap::new())),
+            completions: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// A request from `pid` starting now
+    fn pending_request(&self, pid: u64) -> PendingRequest {
+        PendingRequest {
+            pid,
+            submitted_at: self.now_nanos(),
         }
     }
 
@@ -99,7 +123,7 @@ impl WasmHal {
                 ));
                 return false;
             }
-            pending.insert(request_id, pid);
+            pending.insert(request_id, self.pending_request(pid));
             true
         } else {
             false
@@ -118,7 +142,7 @@ impl WasmHal {
                 ));
                 return false;
             }
-            pending.insert(request_id, pid);
+            pending.insert(request_id, self.pending_request(pid));
             true
         } else {
             false
@@ -142,7 +166,7 @@ impl WasmHal {
                 ));
                 return false;
             }
-            pending.insert(request_id, pid);
+            pending.insert(request_id, self.pending_request(pid));
             true
         } else {
             false
@@ -287,6 +311,44 @@ impl WasmHal {
         data
     }
 
+    /// Copy `len` bytes between a worker's memory at `addr` and a shared
+    /// memory region at `offset`: into the region if `into_region`, out of
+    /// it otherwise.
+    ///
+    /// Returns false if the worker's memory, as last sent by the worker,
+    /// does not cover the bytes (it may have grown since), or the region is
+    /// too small.
+    pub fn copy_shm(
+        &self,
+        pid: u64,
+        addr: u32,
+        region: &js_sys::SharedArrayBuffer,
+        offset: u32,
+        len: u32,
+        into_region: bool,
+    ) -> bool {
+        let Ok(processes) = self.processes.lock() else {
+            return false;
+        };
+        let Some(proc) = processes.get(&pid).filter(|p| p.worker_id != 0) else {
+            return false;
+        };
+        let fits = |start: u32, size: u32| start.checked_add(len).is_some_and(|end| end <= size);
+        if !fits(addr, proc.syscall_buffer.byte_length()) || !fits(offset, region.byte_length()) {
+            return false;
+        }
+
+        let memory =
+            js_sys::Uint8Array::new_with_byte_offset_and_length(&proc.syscall_buffer, addr, len);
+        let shared = js_sys::Uint8Array::new_with_byte_offset_and_length(region, offset, len);
+        if into_region {
+            shared.set(&memory, 0);
+        } else {
+            memory.set(&shared, 0);
+        }
+        true
+    }
+
     /// Write data to a worker's syscall result buffer
     pub fn write_syscall_data(&self, pid: u64, data: &[u8]) {
         if let Ok(processes) = self.processes.lock() {
@@ -355,6 +417,10 @@ impl HAL for WasmHal {
         self.do_get_process_memory_size(handle)
     }
 
+    fn read_process_memory(&self, pid: u64, addr: u32, len: u32) -> Result<Vec<u8>, HalError> {
+        self.do_read_process_memory(pid, addr, len)
+    }
+
     fn allocate(&self, size: usize, _align: usize) -> Result<*mut u8, HalError> {
         // In WASM, use the standard allocator
         let layout =
@@ -459,6 +525,10 @@ impl HAL for WasmHal {
         self.do_storage_batch_write_async(pid, items)
     }
 
+    fn storage_cancel(&self, pid: u64, request_id: StorageRequestId) -> Result<(), HalError> {
+        self.do_storage_cancel(pid, request_id)
+    }
+
     fn get_storage_request_pid(&self, request_id: StorageRequestId) -> Option<u64> {
         self.do_get_storage_request_pid(request_id)
     }
@@ -537,4 +607,21 @@ impl HAL for WasmHal {
     fn take_network_request_pid(&self, request_id: NetworkRequestId) -> Option<u64> {
         self.do_take_network_request_pid(request_id)
     }
+
+    fn poll_async_completions(&self) -> Vec<AsyncCompletion> {
+        self.do_poll_async_completions()
+    }
+
+    fn pending_async_ops(&self) -> Vec<PendingAsyncOp> {
+        self.do_pending_async_ops()
+    }
+
+    fn fail_async(
+        &self,
+        channel: AsyncChannel,
+        request_id: u32,
+        reason: &str,
+    ) -> Result<(), HalError> {
+        self.do_fail_async(channel, request_id, reason)
+    }
 }
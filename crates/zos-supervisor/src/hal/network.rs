@@ -112,7 +112,7 @@ impl WasmHal {
         self.pending_network_requests
             .lock()
             .ok()
-            .and_then(|pending| pending.get(&request_id).copied())
+            .and_then(|pending| pending.get(&request_id).map(|request| request.pid))
     }
 
     /// Take (remove) the PID associated with a network request
@@ -121,5 +121,6 @@ impl WasmHal {
             .lock()
             .ok()
             .and_then(|mut pending| pending.remove(&request_id))
+            .map(|request| request.pid)
     }
 }
@@ -344,4 +344,32 @@ impl WasmHal {
             .map(|p| p.memory_size)
             .ok_or(HalError::ProcessNotFound)
     }
+
+    /// Read bytes of a worker's memory, as last sent by the worker
+    pub fn do_read_process_memory(
+        &self,
+        pid: u64,
+        addr: u32,
+        len: u32,
+    ) -> Result<Vec<u8>, HalError> {
+        let processes = self.processes.lock().map_err(|_| HalError::ProcessNotFound)?;
+        let proc = processes
+            .get(&pid)
+            .filter(|p| p.alive)
+            .ok_or(HalError::ProcessNotFound)?;
+        // Before the worker sends its memory there is nothing to read
+        if proc.worker_id == 0 {
+            return Err(HalError::NotSupported);
+        }
+        let in_bounds = addr
+            .checked_add(len)
+            .is_some_and(|end| end <= proc.syscall_buffer.byte_length());
+        if !in_bounds {
+            return Err(HalError::InvalidArgument);
+        }
+
+        let memory =
+            js_sys::Uint8Array::new_with_byte_offset_and_length(&proc.syscall_buffer, addr, len);
+        Ok(memory.to_vec())
+    }
 }
@@ -201,6 +201,30 @@ pub(crate) fn start_storage_batch_write(request_id: u32, items: &[(&str, &[u8])]
     ));
 }
 
+/// Ask ZosStorage to abort a pending request; it reports back via notify_storage_cancelled
+pub(crate) fn start_storage_cancel(request_id: u32) {
+    if let Some(window) = web_sys::window() {
+        let zos_storage = js_sys::Reflect::get(&window, &"ZosStorage".into()).ok();
+        if let Some(storage) = zos_storage {
+            if !storage.is_undefined() {
+                let _ = js_sys::Reflect::apply(
+                    &js_sys::Reflect::get(&storage, &"cancelRequest".into())
+                        .ok()
+                        .and_then(|f| f.dyn_into::<js_sys::Function>().ok())
+                        .unwrap_or_else(|| js_sys::Function::new_no_args("")),
+                    &storage,
+                    &js_sys::Array::of1(&request_id.into()),
+                );
+                return;
+            }
+        }
+    }
+    log(&format!(
+        "[wasm-hal] ZosStorage.cancelRequest not available for request_id={}",
+        request_id
+    ));
+}
+
 // =============================================================================
 // Keystore Helper Functions (ZosKeystore)
 // =============================================================================
@@ -474,12 +498,28 @@ impl WasmHal {
         Ok(request_id)
     }
 
+    /// Cancel a storage request started by `pid`
+    ///
+    /// The request stays pending until ZosStorage reports its outcome, so the
+    /// result (cancelled or not) still reaches the process.
+    pub fn do_storage_cancel(&self, pid: u64, request_id: StorageRequestId) -> Result<(), HalError> {
+        match self.do_get_storage_request_pid(request_id) {
+            Some(owner) if owner == pid => {}
+            Some(_) => return Err(HalError::InvalidArgument),
+            None => return Err(HalError::NotFound),
+        }
+
+        start_storage_cancel(request_id);
+
+        Ok(())
+    }
+
     /// Get the PID associated with a storage request
     pub fn do_get_storage_request_pid(&self, request_id: StorageRequestId) -> Option<u64> {
         self.pending_storage_requests
             .lock()
             .ok()
-            .and_then(|pending| pending.get(&request_id).copied())
+            .and_then(|pending| pending.get(&request_id).map(|request| request.pid))
     }
 
     /// Take (remove) the PID associated with a storage request
@@ -488,6 +528,7 @@ impl WasmHal {
             .lock()
             .ok()
             .and_then(|mut pending| pending.remove(&request_id))
+            .map(|request| request.pid)
     }
 
     // === Bootstrap Storage (Supervisor Only) ===
@@ -745,7 +786,7 @@ impl WasmHal {
         self.pending_keystore_requests
             .lock()
             .ok()
-            .and_then(|pending| pending.get(&request_id).copied())
+            .and_then(|pending| pending.get(&request_id).map(|request| request.pid))
     }
 
     /// Take (remove) the PID associated with a keystore request
@@ -754,5 +795,6 @@ impl WasmHal {
             .lock()
             .ok()
             .and_then(|mut pending| pending.remove(&request_id))
+            .map(|request| request.pid)
     }
 }
@@ -0,0 +1,87 @@
+//! Desktop automation - scripts sent by processes to the desktop engine
+//!
+//! A process sends `DESKTOP:SCRIPT:{hex_json}` where the JSON is a list of
+//! script steps (see `zos_desktop::script`). The desktop engine lives in the
+//! `DesktopController`, so the supervisor hands the script to JS through the
+//! registered callback and routes the returned report back to the sender as
+//! `MSG_DESKTOP_SCRIPT_RESULT`.
+//!
+//! Scripts can drive every window on screen, so they are refused unless the
+//! `desktop.automation` feature flag is on.
+
+use wasm_bindgen::prelude::*;
+use zos_flags::known;
+use zos_ipc::desktop::MSG_DESKTOP_SCRIPT_RESULT;
+use zos_kernel::ProcessId;
+
+use super::Supervisor;
+use crate::constants::SERVICE_INPUT_SLOT;
+use crate::util::{hex_to_bytes, log};
+
+/// Report for a script that never reached the engine.
+fn failure_report(message: &str) -> String {
+    serde_json::json!({
+        "outputs": [],
+        "failure": { "step": 0, "message": message },
+        "now_ms": 0.0,
+    })
+    .to_string()
+}
+
+#[wasm_bindgen]
+impl Supervisor {
+    /// Register the callback that runs automation scripts.
+    ///
+    /// Called with the script JSON; must return the report JSON from
+    /// `DesktopController.run_script`.
+    #[wasm_bindgen]
+    pub fn set_desktop_script_callback(&mut self, callback: js_sys::Function) {
+        self.desktop_script_callback = Some(callback);
+        log("[supervisor] Desktop script callback registered");
+    }
+}
+
+impl Supervisor {
+    /// Handle DESKTOP:SCRIPT from a process.
+    pub(super) fn handle_debug_desktop_script(&mut self, pid: ProcessId, hex_data: &str) {
+        let report = self.run_desktop_script(pid, hex_data);
+        self.route_ipc_via_init(
+            pid.0,
+            SERVICE_INPUT_SLOT,
+            MSG_DESKTOP_SCRIPT_RESULT,
+            report.as_bytes(),
+        );
+    }
+
+    fn run_desktop_script(&self, pid: ProcessId, hex_data: &str) -> String {
+        if !self.feature_flags.is_enabled(known::DESKTOP_AUTOMATION) {
+            log(&format!(
+                "[supervisor] SECURITY: refusing DESKTOP:SCRIPT from PID {} (automation disabled)",
+                pid.0
+            ));
+            return failure_report("desktop automation is disabled");
+        }
+        let Some(script) = hex_to_bytes(hex_data)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+        else {
+            return failure_report("malformed script payload");
+        };
+        let Some(callback) = &self.desktop_script_callback else {
+            return failure_report("no desktop attached");
+        };
+
+        match callback.call1(&JsValue::NULL, &JsValue::from_str(&script)) {
+            Ok(result) => result
+                .as_string()
+                .unwrap_or_else(|| failure_report("desktop returned no report")),
+            Err(e) => {
+                log(&format!(
+                    "[supervisor] Desktop script callback failed: {:?}",
+                    e
+                ));
+                failure_report("desktop script callback failed")
+            }
+        }
+    }
+}
@@ -83,7 +83,7 @@ impl Supervisor {
 
         log("[supervisor] Requesting init spawn...");
         self.write_console("Starting init process...\n");
-        self.request_spawn("init", "init");
+        self.request_spawn("init", "init", None);
     }
 
     /// Initialize the supervisor as a kernel process (PID 0).
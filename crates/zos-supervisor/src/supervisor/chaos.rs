@@ -0,0 +1,219 @@
+//! Chaos mode - fault injection at the supervisor boundary
+//!
+//! Fault injection is toggled at runtime by the `chaos.*` feature flags. When
+//! any of them is enabled the supervisor seeds the kernel's
+//! [`FaultInjector`](zos_kernel::FaultInjector), which records the seed in
+//! the CommitLog. The kernel fails IPC sends itself; the supervisor applies
+//! the boundary-layer faults:
+//!
+//! - Storage results are delayed (held until due) or dropped
+//! - Random non-core processes are killed via Init
+//!
+//! To reproduce a failure, read the seed from the `FaultInjectionSeeded`
+//! commit (or `get_chaos_seed()`), call `set_chaos_seed()` before the flags
+//! load, and repeat the run.
+
+use wasm_bindgen::prelude::*;
+use zos_flags::known;
+use zos_hal::HAL;
+use zos_kernel::chaos::{FAULT_IPC, FAULT_KILL, FAULT_STORAGE};
+use zos_kernel::{ChaosConfig, ProcessId, StorageFault};
+
+use super::Supervisor;
+use crate::util::log;
+
+/// How often a kill check runs.
+const KILL_CHECK_INTERVAL_MS: u64 = 1000;
+
+/// Upper bound on held-back storage results (oldest are delivered early).
+const MAX_DELAYED_STORAGE_RESULTS: usize = 256;
+
+/// Processes fault injection never kills.
+const CORE_PROCESSES: &[&str] = &[
+    "supervisor",
+    "init",
+    "permission",
+    "vfs",
+    "keystore",
+    "identity",
+    "time",
+    "network",
+    "update",
+    "flags",
+];
+
+/// A storage result held back by fault injection.
+pub(super) struct DelayedStorageResult {
+    due_ms: u64,
+    pid: u64,
+    payload: Vec<u8>,
+}
+
+/// Chaos mode state kept by the supervisor.
+#[derive(Default)]
+pub(super) struct ChaosState {
+    /// Seed to use the next time fault injection is enabled
+    seed_override: Option<u64>,
+    /// Storage results waiting for their delay to elapse
+    delayed_storage: Vec<DelayedStorageResult>,
+    /// Uptime of the last kill check
+    last_kill_check_ms: u64,
+}
+
+#[wasm_bindgen]
+impl Supervisor {
+    /// Set the seed used when chaos mode is next enabled.
+    ///
+    /// Accepts a decimal or `0x`-prefixed hex u64. Returns false if the
+    /// seed cannot be parsed.
+    #[wasm_bindgen]
+    pub fn set_chaos_seed(&mut self, seed: &str) -> bool {
+        let parsed = match seed.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => seed.parse::<u64>().ok(),
+        };
+        match parsed {
+            Some(value) => {
+                self.chaos.seed_override = Some(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Seed of the active fault injector, or `undefined` if chaos mode is off.
+    #[wasm_bindgen]
+    pub fn get_chaos_seed(&self) -> Option<String> {
+        self.system
+            .fault_injector()
+            .map(|f| format!("{:#018x}", f.seed()))
+    }
+}
+
+impl Supervisor {
+    /// Enable, reconfigure or disable fault injection from the flag snapshot.
+    pub(super) fn apply_chaos_flags(&mut self) {
+        let mut faults = 0u8;
+        if self.feature_flags.is_enabled(known::CHAOS_STORAGE_FAULTS) {
+            faults |= FAULT_STORAGE;
+        }
+        if self.feature_flags.is_enabled(known::CHAOS_IPC_FAULTS) {
+            faults |= FAULT_IPC;
+        }
+        if self.feature_flags.is_enabled(known::CHAOS_PROCESS_KILLS) {
+            faults |= FAULT_KILL;
+        }
+
+        let current = self.system.fault_injector().map(|f| f.config().faults);
+        if current == Some(faults) || (current.is_none() && faults == 0) {
+            return;
+        }
+
+        if faults == 0 {
+            log("[supervisor] Chaos mode disabled");
+            self.system.disable_fault_injection();
+            self.flush_delayed_storage_results(u64::MAX);
+            return;
+        }
+
+        let seed = match self.chaos.seed_override.take() {
+            Some(seed) => seed,
+            None => self.random_chaos_seed(),
+        };
+        log(&format!(
+            "[supervisor] Chaos mode ENABLED (faults={:#04x}, seed={:#018x})",
+            faults, seed
+        ));
+        self.system
+            .enable_fault_injection(seed, ChaosConfig::with_faults(faults));
+    }
+
+    fn random_chaos_seed(&self) -> u64 {
+        let mut bytes = [0u8; 8];
+        match self.system.hal().random_bytes(&mut bytes) {
+            Ok(()) => u64::from_le_bytes(bytes),
+            Err(_) => self.system.hal().wallclock_ms(),
+        }
+    }
+
+    /// Apply storage faults to a result before delivery.
+    ///
+    /// Returns true if the result should be delivered now.
+    pub(super) fn chaos_filter_storage_result(&mut self, pid: u64, payload: &[u8]) -> bool {
+        let Some(injector) = self.system.fault_injector_mut() else {
+            return true;
+        };
+        match injector.storage_fault() {
+            StorageFault::Deliver => true,
+            StorageFault::Drop => {
+                log(&format!(
+                    "[chaos] Dropped storage result for PID {} ({} bytes)",
+                    pid,
+                    payload.len()
+                ));
+                false
+            }
+            StorageFault::Delay { ms } => {
+                if self.chaos.delayed_storage.len() >= MAX_DELAYED_STORAGE_RESULTS {
+                    let oldest = self.chaos.delayed_storage.remove(0);
+                    self.deliver_storage_result_now(oldest.pid, &oldest.payload);
+                }
+                let due_ms = self.uptime_ms() + ms as u64;
+                self.chaos.delayed_storage.push(DelayedStorageResult {
+                    due_ms,
+                    pid,
+                    payload: payload.to_vec(),
+                });
+                false
+            }
+        }
+    }
+
+    /// Periodic chaos work: release due storage results, maybe kill a process.
+    pub(super) fn chaos_tick(&mut self) {
+        let now = self.uptime_ms();
+        if !self.chaos.delayed_storage.is_empty() {
+            self.flush_delayed_storage_results(now);
+        }
+
+        if self.system.fault_injector().is_none()
+            || now.saturating_sub(self.chaos.last_kill_check_ms) < KILL_CHECK_INTERVAL_MS
+        {
+            return;
+        }
+        self.chaos.last_kill_check_ms = now;
+
+        let candidates: Vec<u64> = self
+            .system
+            .list_processes()
+            .into_iter()
+            .filter(|(pid, p)| pid.0 > 1 && !CORE_PROCESSES.contains(&p.name.as_str()))
+            .map(|(pid, _)| pid.0)
+            .collect();
+        let victim = self
+            .system
+            .fault_injector_mut()
+            .and_then(|f| f.pick_victim(&candidates));
+        if let Some(pid) = victim {
+            log(&format!("[chaos] Killing PID {}", pid));
+            self.kill_process(pid);
+        }
+    }
+
+    fn flush_delayed_storage_results(&mut self, now_ms: u64) {
+        let (due, pending): (Vec<_>, Vec<_>) = core::mem::take(&mut self.chaos.delayed_storage)
+            .into_iter()
+            .partition(|r| r.due_ms <= now_ms);
+        self.chaos.delayed_storage = pending;
+        for result in due {
+            // Skip results for processes killed while the result was held
+            if self.system.get_process(ProcessId(result.pid)).is_some() {
+                self.deliver_storage_result_now(result.pid, &result.payload);
+            }
+        }
+    }
+
+    fn uptime_ms(&self) -> u64 {
+        self.system.uptime_nanos() / 1_000_000
+    }
+}
@@ -0,0 +1,61 @@
+//! Async completion delivery
+//!
+//! Storage, keystore and network results reach the supervisor as HAL
+//! completions (see `hal::completion`). Once per poll tick they are
+//! collected through the kernel, which encodes each as the IPC message its
+//! service expects, and handed to the delivery path for their channel:
+//!
+//! - Storage: chaos filter, then the per-process outbox (`storage_batch`)
+//! - Keystore and network: routed through Init straight away
+//!
+//! # Safety Invariants
+//!
+//! ## Success Criteria
+//! - Every completion is delivered once, to the PID that started the request
+//!
+//! ## Forbidden States
+//! - A completion delivered on another channel's tag
+//!
+//! Requests JavaScript never answers can be failed by hand with
+//! `fail_stuck_async_ops`; the service gets an error result instead of
+//! waiting forever.
+
+use wasm_bindgen::prelude::*;
+use zos_hal::AsyncChannel;
+
+use super::Supervisor;
+use crate::util::log;
+
+#[wasm_bindgen]
+impl Supervisor {
+    /// Fail every async operation outstanding for at least `min_age_ms`
+    ///
+    /// Each owner gets an error result on the next tick, as if the backend
+    /// had failed. Returns how many were failed.
+    #[wasm_bindgen]
+    pub fn fail_stuck_async_ops(&mut self, min_age_ms: f64) -> usize {
+        let min_age_nanos = (min_age_ms.max(0.0) * 1_000_000.0) as u64;
+        let failed = self.system.fail_stuck_async_ops(min_age_nanos);
+        for op in &failed {
+            log(&format!(
+                "[supervisor] Force-failed stuck {:?} request {} of PID {}",
+                op.channel, op.request_id, op.pid
+            ));
+        }
+        failed.len()
+    }
+}
+
+impl Supervisor {
+    /// Deliver the async operations that finished since the last tick.
+    pub(super) fn deliver_async_completions(&mut self) {
+        for message in self.system.poll_async_completions() {
+            let pid = message.pid.0;
+            match message.channel {
+                AsyncChannel::Storage => self.deliver_storage_result(pid, &message.payload),
+                AsyncChannel::Keystore => self.deliver_keystore_result(pid, &message.payload),
+                AsyncChannel::Network => self.deliver_network_result(pid, &message.payload),
+            }
+        }
+    }
+}
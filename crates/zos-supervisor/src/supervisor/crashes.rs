@@ -0,0 +1,182 @@
+//! Crash reports
+//!
+//! Init reports every crash of a service or app as
+//! `INIT:CRASH:{pid}:{exit_code}:{recent_crashes}:{failed}:{name}`. The
+//! supervisor keeps the last report per name and passes each one to the JS
+//! crash callback as JSON. A report with `failed` set ended in a crash loop:
+//! Init stops restarting a service, and the desktop marks the app failed and
+//! stops launching it. `reset_crashes` clears a crash loop once the user has
+//! dealt with it (for example by clearing the app's data).
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use zos_kernel::ProcessId;
+
+use super::Supervisor;
+use crate::util::log;
+
+/// Last crash of a service or app.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct CrashReport {
+    /// Process name (the app ID for apps)
+    pub name: String,
+    /// PID of the instance that crashed
+    pub pid: u32,
+    /// Exit code it died with
+    pub exit_code: i32,
+    /// Crashes within Init's crash-loop window, this one included
+    pub recent_crashes: u32,
+    /// In a crash loop: not restarted until reset
+    pub failed: bool,
+    /// Wall-clock time of the report (ms since the Unix epoch)
+    pub time_ms: f64,
+}
+
+/// Last crash report by name.
+pub(crate) type CrashReports = BTreeMap<String, CrashReport>;
+
+/// Parse the part of an `INIT:CRASH:` message after the prefix.
+fn parse_crash_report(rest: &str, time_ms: f64) -> Option<CrashReport> {
+    let mut fields = rest.splitn(5, ':');
+    let pid = fields.next()?.parse().ok()?;
+    let exit_code = fields.next()?.parse().ok()?;
+    let recent_crashes = fields.next()?.parse().ok()?;
+    let failed = match fields.next()? {
+        "0" => false,
+        "1" => true,
+        _ => return None,
+    };
+    let name = fields.next().filter(|name| !name.is_empty())?;
+    Some(CrashReport {
+        name: name.to_string(),
+        pid,
+        exit_code,
+        recent_crashes,
+        failed,
+        time_ms,
+    })
+}
+
+#[wasm_bindgen]
+impl Supervisor {
+    /// Register the callback told about crashes.
+    ///
+    /// Called as `callback(reportJson)` with `{ name, pid, exit_code,
+    /// recent_crashes, failed, time_ms }`.
+    #[wasm_bindgen]
+    pub fn set_crash_callback(&mut self, callback: js_sys::Function) {
+        self.crash_callback = Some(callback);
+        log("[supervisor] Crash callback registered");
+    }
+
+    /// Last crash report of every name that has crashed, as a JSON array.
+    #[wasm_bindgen]
+    pub fn get_crash_reports_json(&self) -> String {
+        let reports: Vec<&CrashReport> = self.crash_reports.values().collect();
+        serde_json::to_string(&reports).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Clear the crash loop of a service or app.
+    ///
+    /// Init forgets its crashes and restarts it if it is a service the crash
+    /// loop stopped. Its last report stays, no longer marked failed.
+    ///
+    /// Returns true if the request was sent to Init.
+    #[wasm_bindgen]
+    pub fn reset_crashes(&mut self, name: &str) -> bool {
+        use zos_ipc::supervisor::MSG_SUPERVISOR_RESET_CRASHES;
+
+        let Some(init_slot) = self.init_endpoint_slot else {
+            log("[supervisor] Cannot reset crashes: no Init capability");
+            return false;
+        };
+        match self.system.ipc_send(
+            ProcessId(0),
+            init_slot,
+            MSG_SUPERVISOR_RESET_CRASHES,
+            name.as_bytes().to_vec(),
+        ) {
+            Ok(()) => {
+                if let Some(report) = self.crash_reports.get_mut(name) {
+                    report.failed = false;
+                }
+                log(&format!(
+                    "[supervisor] Sent crash reset for {} to Init",
+                    name
+                ));
+                true
+            }
+            Err(e) => {
+                log(&format!(
+                    "[supervisor] Failed to send crash reset to Init: {:?}",
+                    e
+                ));
+                false
+            }
+        }
+    }
+}
+
+impl Supervisor {
+    /// Handle INIT:CRASH: from Init.
+    pub(super) fn handle_init_crash(&mut self, pid: ProcessId, rest: &str) {
+        if pid.0 != 1 {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring INIT:CRASH from PID {}",
+                pid.0
+            ));
+            return;
+        }
+        let Some(report) = parse_crash_report(rest, js_sys::Date::now()) else {
+            log(&format!("[supervisor] Malformed INIT:CRASH: {}", rest));
+            return;
+        };
+        log(&format!(
+            "[supervisor] {} (PID {}) crashed with code {} ({} recent{})",
+            report.name,
+            report.pid,
+            report.exit_code,
+            report.recent_crashes,
+            if report.failed { ", crash loop" } else { "" }
+        ));
+
+        let json = serde_json::to_string(&report).unwrap_or_default();
+        self.crash_reports.insert(report.name.clone(), report);
+        if let Some(callback) = &self.crash_callback {
+            if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from_str(&json)) {
+                log(&format!("[supervisor] Crash callback failed: {:?}", e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_crash_report() {
+        let report = parse_crash_report("12:-1:5:1:calculator", 1000.0).unwrap();
+        assert_eq!(
+            report,
+            CrashReport {
+                name: "calculator".to_string(),
+                pid: 12,
+                exit_code: -1,
+                recent_crashes: 5,
+                failed: true,
+                time_ms: 1000.0,
+            }
+        );
+
+        // The name is the rest of the message
+        let report = parse_crash_report("3:1:1:0:a:b", 0.0).unwrap();
+        assert_eq!((report.name.as_str(), report.failed), ("a:b", false));
+
+        assert_eq!(parse_crash_report("3:1:1:2:vfs", 0.0), None);
+        assert_eq!(parse_crash_report("3:1:1:0:", 0.0), None);
+        assert_eq!(parse_crash_report("x:1:1:0:vfs", 0.0), None);
+    }
+}
@@ -5,8 +5,21 @@
 //!
 //! - Spawn requests (INIT:SPAWN:)
 //! - Capability operations (INIT:GRANT:, INIT:REVOKE:)
+//! - Crash reports (INIT:CRASH:)
 //! - Permission responses
 //! - Service IPC responses
+//! - VFS pending operation counters (VFS:STATS:)
+//! - Feature flag snapshots (FLAGS:SNAPSHOT:)
+//! - Network service responses (NET:RESPONSE:)
+//! - Network traffic counters (NET:STATS:)
+//! - Desktop automation scripts (DESKTOP:SCRIPT:)
+//! - Speech output (SPEECH:SPEAK:, SPEECH:CANCEL)
+//! - Print dialog jobs (PRINT:DIALOG:)
+//! - Permission prompts (PERMSVC:PROMPT:)
+//! - Thumbnail decodes (THUMBNAIL:DECODE:)
+//! - Event bus deliveries (EVENT:DELIVER:)
+//! - Command palette registrations and invocations (COMMAND:REGISTER:, COMMAND:DELIVER:)
+//! - Desktop notifications (NOTIFY:SHOW:)
 //! - Console output
 
 use zos_hal::HAL;
@@ -22,8 +35,8 @@ impl Supervisor {
     /// Dispatch debug message to appropriate handler based on prefix.
     pub(super) fn dispatch_debug_message(&mut self, pid: ProcessId, msg: &str) {
         // Try each handler in order of specificity
-        if let Some(service_name) = msg.strip_prefix(debug::INIT_SPAWN) {
-            self.handle_debug_spawn(service_name);
+        if let Some(request) = msg.strip_prefix(debug::INIT_SPAWN) {
+            self.handle_debug_spawn(pid, request);
         } else if msg.starts_with(debug::INIT_GRANT) {
             syscall::handle_init_grant(&mut self.system, msg);
         } else if msg.starts_with(debug::INIT_REVOKE) {
@@ -32,6 +45,8 @@ impl Supervisor {
             self.handle_init_kill_ok(rest);
         } else if let Some(rest) = msg.strip_prefix(debug::INIT_KILL_FAIL) {
             self.handle_init_kill_fail(rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::INIT_CRASH) {
+            self.handle_init_crash(pid, rest);
         } else if msg.starts_with(debug::INIT_PERM_RESPONSE) {
             log(&format!("[supervisor] Permission response: {}", msg));
         } else if msg.starts_with(debug::INIT_PERM_LIST) {
@@ -39,11 +54,39 @@ impl Supervisor {
         } else if let Some(init_msg) = msg.strip_prefix(debug::INIT_PREFIX) {
             log(&format!("[init] {}", init_msg));
         } else if let Some(rest) = msg.strip_prefix(debug::SERVICE_RESPONSE) {
-            self.handle_debug_service_response(rest);
+            self.handle_debug_service_response(pid, rest);
         } else if let Some(rest) = msg.strip_prefix(debug::VFS_RESPONSE) {
             self.handle_debug_vfs_response(rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::VFS_STATS) {
+            self.handle_debug_vfs_stats(pid, rest);
         } else if let Some(rest) = msg.strip_prefix(debug::KEYSTORE_RESPONSE) {
             self.handle_debug_keystore_response(rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::FLAGS_SNAPSHOT) {
+            self.handle_debug_flags_snapshot(pid, rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::NET_RESPONSE) {
+            self.handle_debug_net_response(pid, rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::NET_STATS) {
+            self.handle_debug_net_stats(pid, rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::DESKTOP_SCRIPT) {
+            self.handle_debug_desktop_script(pid, rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::SPEECH_SPEAK) {
+            self.handle_debug_speech_speak(pid, rest);
+        } else if msg == debug::SPEECH_CANCEL {
+            self.handle_debug_speech_cancel(pid);
+        } else if let Some(rest) = msg.strip_prefix(debug::PRINT_DIALOG) {
+            self.handle_debug_print_dialog(pid, rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::PERMSVC_PROMPT) {
+            self.handle_debug_permission_prompt(pid, rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::THUMBNAIL_DECODE) {
+            self.handle_debug_thumbnail_decode(pid, rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::EVENT_DELIVER) {
+            self.handle_debug_event_deliver(pid, rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::COMMAND_REGISTER) {
+            self.handle_debug_command_register(pid, rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::COMMAND_DELIVER) {
+            self.handle_debug_command_deliver(pid, rest);
+        } else if let Some(rest) = msg.strip_prefix(debug::NOTIFY_SHOW) {
+            self.handle_debug_notify_show(pid, rest);
         // Init-driven spawn protocol responses
         } else if let Some(rest) = msg.strip_prefix(debug::SPAWN_RESPONSE) {
             self.handle_init_spawn_response(rest);
@@ -265,12 +308,25 @@ impl Supervisor {
     }
 
     /// Handle INIT:SPAWN: debug message.
-    fn handle_debug_spawn(&mut self, service_name: &str) {
+    ///
+    /// Format: "INIT:SPAWN:{name}", or "INIT:SPAWN:{name}:{requester_pid}"
+    /// from Init. The new process joins the requester's process group; Init
+    /// spawning for itself (e.g. at boot) gives it a group of its own.
+    fn handle_debug_spawn(&mut self, pid: ProcessId, request: &str) {
+        let (service_name, requester) = match request.split_once(':') {
+            Some((name, requester)) if pid.0 == 1 => (name, requester.parse().ok().map(ProcessId)),
+            _ if pid.0 == 1 => (request, None),
+            _ => (request, Some(pid)),
+        };
         log(&format!(
-            "[supervisor] Init requesting spawn of '{}'",
-            service_name
+            "[supervisor] PID {} requesting spawn of '{}'",
+            pid.0, service_name
         ));
-        self.request_spawn(service_name, service_name);
+
+        let group = requester
+            .and_then(|requester| self.system.get_process(requester))
+            .map(|proc| proc.pgid);
+        self.request_spawn(service_name, service_name, group);
     }
 
     /// Handle default debug message (console output).
@@ -0,0 +1,66 @@
+//! Feature flag snapshot
+//!
+//! The FeatureFlagService publishes the resolved system-wide flags as
+//! `FLAGS:SNAPSHOT:{hex_json}` whenever they load or change. The supervisor
+//! caches the latest snapshot so JS and kernel-side wrappers can gate risky
+//! subsystems at startup without an IPC round-trip.
+
+use wasm_bindgen::prelude::*;
+use zos_flags::FeatureFlags;
+use zos_kernel::ProcessId;
+
+use super::Supervisor;
+use crate::util::{hex_to_bytes, log};
+
+#[wasm_bindgen]
+impl Supervisor {
+    /// Whether a feature flag is enabled system-wide.
+    ///
+    /// Returns built-in defaults until the FeatureFlagService has published
+    /// its first snapshot.
+    #[wasm_bindgen]
+    pub fn is_feature_enabled(&self, name: &str) -> bool {
+        self.feature_flags.is_enabled(name)
+    }
+
+    /// Get the current feature flag snapshot as JSON
+    #[wasm_bindgen]
+    pub fn get_feature_flags_json(&self) -> String {
+        String::from_utf8(self.feature_flags.to_json()).unwrap_or_default()
+    }
+}
+
+impl Supervisor {
+    /// Handle FLAGS:SNAPSHOT:{hex_json} from the FeatureFlagService.
+    ///
+    /// Snapshots from any other process are ignored.
+    pub(super) fn handle_debug_flags_snapshot(&mut self, pid: ProcessId, hex_data: &str) {
+        if self.find_service_pid("flags") != Some(pid) {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring FLAGS:SNAPSHOT from PID {}",
+                pid.0
+            ));
+            return;
+        }
+        let Some(flags) = hex_to_bytes(hex_data)
+            .ok()
+            .and_then(|bytes| FeatureFlags::from_json(&bytes))
+        else {
+            log("[supervisor] Malformed FLAGS:SNAPSHOT, keeping previous flags");
+            return;
+        };
+
+        let enabled: Vec<&str> = flags
+            .flags
+            .iter()
+            .filter(|(_, on)| **on)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        log(&format!(
+            "[supervisor] Feature flags updated, enabled: [{}]",
+            enabled.join(", ")
+        ));
+        self.feature_flags = flags;
+        self.apply_chaos_flags();
+    }
+}
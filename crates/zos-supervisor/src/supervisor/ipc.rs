@@ -6,9 +6,20 @@
 
 use wasm_bindgen::prelude::*;
 use zos_kernel::ProcessId;
+use zos_vfs::ipc::VfsStats;
 
 use crate::util::{hex_to_bytes, log};
 
+/// Services whose replies to another service are routed via Init rather
+/// than to JS: (replying service, client service).
+const SERVICE_REPLY_ROUTES: &[(&str, &str)] = &[
+    ("time", "calendar"),
+    ("identity", "contacts"),
+    ("keystore", "messaging"),
+    ("contacts", "messaging"),
+    ("messaging", "clipboard"),
+];
+
 impl super::Supervisor {
     /// Route console input through Init (fallback when supervisor lacks capability)
     pub(super) fn route_console_input_via_init(&mut self, target_pid: u64, input: &str) {
@@ -153,6 +164,26 @@ impl super::Supervisor {
         self.route_ipc_via_init(to_pid as u64, SERVICE_INPUT_SLOT, tag, &data);
     }
 
+    /// Handle VFS:STATS:{hex_json} from the VfsService.
+    ///
+    /// Snapshots from any other process are ignored.
+    pub(super) fn handle_debug_vfs_stats(&mut self, pid: ProcessId, hex_data: &str) {
+        if self.find_service_pid("vfs") != Some(pid) {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring VFS:STATS from PID {}",
+                pid.0
+            ));
+            return;
+        }
+        match hex_to_bytes(hex_data)
+            .ok()
+            .and_then(|bytes| VfsStats::from_json(&bytes))
+        {
+            Some(stats) => self.vfs_stats = stats,
+            None => log("[supervisor] Malformed VFS:STATS, keeping previous counters"),
+        }
+    }
+
     /// Handle KEYSTORE:RESPONSE: debug message.
     ///
     /// Format: {to_pid}:{tag_hex}:{hex_data}
@@ -208,11 +239,130 @@ impl super::Supervisor {
         self.route_ipc_via_init(to_pid as u64, SERVICE_INPUT_SLOT, tag, &data);
     }
 
+    /// Handle EVENT:DELIVER: debug message.
+    ///
+    /// Format: {to_pid}:{tag_hex}:{hex_data}
+    /// Example: "12:00008606:7b22..."
+    /// Routes an event to a subscriber via Init. Only the EventBusService may
+    /// deliver, and only MSG_EVENT, so it cannot forge other messages.
+    pub(super) fn handle_debug_event_deliver(&mut self, pid: ProcessId, rest: &str) {
+        if self.find_service_pid("events") != Some(pid) {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring EVENT:DELIVER from PID {}",
+                pid.0
+            ));
+            return;
+        }
+
+        let parts: Vec<&str> = rest.splitn(3, ':').collect();
+        let parsed = match parts.as_slice() {
+            [to_pid, tag, hex] => to_pid
+                .parse::<u32>()
+                .ok()
+                .zip(u32::from_str_radix(tag, 16).ok())
+                .zip(hex_to_bytes(hex).ok()),
+            _ => None,
+        };
+        let Some(((to_pid, tag), data)) = parsed else {
+            log(&format!("[supervisor] Malformed EVENT:DELIVER: {}", rest));
+            return;
+        };
+        if tag != zos_ipc::events::MSG_EVENT {
+            log(&format!(
+                "[supervisor] SECURITY: EVENT:DELIVER with tag 0x{:x} refused",
+                tag
+            ));
+            return;
+        }
+
+        use crate::constants::SERVICE_INPUT_SLOT;
+        self.route_ipc_via_init(to_pid as u64, SERVICE_INPUT_SLOT, tag, &data);
+    }
+
+    /// Handle COMMAND:REGISTER: debug message.
+    ///
+    /// Format: {hex_json}, the `{"app", "commands"}` of an app's manifest.
+    /// Forwarded to the CommandService as MSG_COMMAND_REGISTER via Init,
+    /// with the sender's PID as the owner: any process may announce its own
+    /// commands, but the PID it claims (if any) is replaced.
+    pub(super) fn handle_debug_command_register(&mut self, pid: ProcessId, hex_data: &str) {
+        let Some(service_pid) = self.find_service_pid("commands") else {
+            log(&format!(
+                "[supervisor] COMMAND:REGISTER from PID {} before CommandService is up",
+                pid.0
+            ));
+            return;
+        };
+        let request = hex_to_bytes(hex_data)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok());
+        let Some(serde_json::Value::Object(mut request)) = request else {
+            log(&format!(
+                "[supervisor] Malformed COMMAND:REGISTER from PID {}",
+                pid.0
+            ));
+            return;
+        };
+        request.insert("pid".to_string(), serde_json::Value::from(pid.0));
+        let data = serde_json::to_vec(&request).unwrap_or_default();
+
+        use crate::constants::SERVICE_INPUT_SLOT;
+        self.route_ipc_via_init(
+            service_pid.0,
+            SERVICE_INPUT_SLOT,
+            zos_ipc::commands::MSG_COMMAND_REGISTER,
+            &data,
+        );
+    }
+
+    /// Handle COMMAND:DELIVER: debug message.
+    ///
+    /// Format: {to_pid}:{tag_hex}:{hex_data}
+    /// Example: "12:00008d08:7b22..."
+    /// Routes an invoked command to its app via Init. Only the CommandService
+    /// may deliver, and only MSG_COMMAND, so it cannot forge other messages.
+    pub(super) fn handle_debug_command_deliver(&mut self, pid: ProcessId, rest: &str) {
+        if self.find_service_pid("commands") != Some(pid) {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring COMMAND:DELIVER from PID {}",
+                pid.0
+            ));
+            return;
+        }
+
+        let parts: Vec<&str> = rest.splitn(3, ':').collect();
+        let parsed = match parts.as_slice() {
+            [to_pid, tag, hex] => to_pid
+                .parse::<u32>()
+                .ok()
+                .zip(u32::from_str_radix(tag, 16).ok())
+                .zip(hex_to_bytes(hex).ok()),
+            _ => None,
+        };
+        let Some(((to_pid, tag), data)) = parsed else {
+            log(&format!("[supervisor] Malformed COMMAND:DELIVER: {}", rest));
+            return;
+        };
+        if tag != zos_ipc::commands::MSG_COMMAND {
+            log(&format!(
+                "[supervisor] SECURITY: COMMAND:DELIVER with tag 0x{:x} refused",
+                tag
+            ));
+            return;
+        }
+
+        use crate::constants::SERVICE_INPUT_SLOT;
+        self.route_ipc_via_init(to_pid as u64, SERVICE_INPUT_SLOT, tag, &data);
+    }
+
     /// Handle SERVICE:RESPONSE: debug message.
     ///
     /// Format: {to_pid}:{tag_hex}:{hex_data}
     /// Example: "0:00007055:7b22..."
-    pub(super) fn handle_debug_service_response(&self, rest: &str) {
+    /// Responses go to the JS callback, except replies between services
+    /// listed in `SERVICE_REPLY_ROUTES`, which JS cannot reach: those are
+    /// routed via Init.
+    pub(super) fn handle_debug_service_response(&mut self, pid: ProcessId, rest: &str) {
         let parts: Vec<&str> = rest.splitn(3, ':').collect();
         if parts.len() != 3 {
             log(&format!(
@@ -222,6 +372,18 @@ impl super::Supervisor {
             return;
         }
 
+        if let Some(client_pid) = self.service_reply_route(pid, parts[0]) {
+            let tag = u32::from_str_radix(parts[1], 16).ok();
+            let data = hex_to_bytes(parts[2]).ok();
+            let (Some(tag), Some(data)) = (tag, data) else {
+                log(&format!("[supervisor] Malformed SERVICE:RESPONSE: {}", rest));
+                return;
+            };
+            use crate::constants::SERVICE_INPUT_SLOT;
+            self.route_ipc_via_init(client_pid.0, SERVICE_INPUT_SLOT, tag, &data);
+            return;
+        }
+
         let request_id = parts[1]; // tag hex is the request_id
         let hex_data = parts[2];
 
@@ -266,6 +428,18 @@ impl super::Supervisor {
         ));
     }
 
+    /// The client service's PID, if a SERVICE:RESPONSE from `pid` to
+    /// `to_pid` is one service answering another (see `SERVICE_REPLY_ROUTES`).
+    fn service_reply_route(&self, pid: ProcessId, to_pid: &str) -> Option<ProcessId> {
+        let to_pid = to_pid.parse::<u64>().ok()?;
+        SERVICE_REPLY_ROUTES.iter().find_map(|&(server, client)| {
+            if self.find_service_pid(server) != Some(pid) {
+                return None;
+            }
+            self.find_service_pid(client).filter(|c| c.0 == to_pid)
+        })
+    }
+
     /// Find a service process by name.
     ///
     /// Looks up the service by its exact name in the process list.
@@ -164,6 +164,8 @@ impl Supervisor {
                     "ipc_sent": proc.metrics.ipc_sent,
                     "ipc_received": proc.metrics.ipc_received,
                     "syscalls": proc.metrics.syscall_count,
+                    "run_time_ns": proc.metrics.run_time_ns,
+                    "wakeups": proc.metrics.wakeups,
                     "worker_id": worker_id
                 })
             })
@@ -187,6 +189,8 @@ impl Supervisor {
                         zos_kernel::ObjectType::Irq => "IRQ",
                         zos_kernel::ObjectType::IoPort => "IoPort",
                         zos_kernel::ObjectType::Console => "Console",
+                        zos_kernel::ObjectType::Notification => "Notification",
+                        zos_kernel::ObjectType::Debugger => "Debugger",
                     };
                     serde_json::json!({
                         "slot": slot,
@@ -233,6 +237,8 @@ impl Supervisor {
                                     zos_kernel::ObjectType::Irq => "IRQ",
                                     zos_kernel::ObjectType::IoPort => "IoPort",
                                     zos_kernel::ObjectType::Console => "Console",
+                                    zos_kernel::ObjectType::Notification => "Notification",
+                                    zos_kernel::ObjectType::Debugger => "Debugger",
                                 };
                                 serde_json::json!({
                                     "slot": slot,
@@ -281,6 +287,90 @@ impl Supervisor {
         serde_json::to_string(&endpoints).unwrap_or_else(|_| "[]".to_string())
     }
 
+    /// Get the service graph as JSON for the system map
+    ///
+    /// `{ nodes: [{ pid, name, services, endpoints }], edges: [{ from, to,
+    /// capabilities, messages }] }`: which processes hold capabilities to
+    /// which others' objects, and how many messages they have sent them.
+    #[wasm_bindgen]
+    pub fn get_service_graph_json(&self) -> String {
+        serde_json::to_string(&self.system.service_graph()).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Get in-flight async operations (storage, keystore, network) as JSON,
+    /// oldest first
+    ///
+    /// Each entry has the channel, request ID, owning PID and process name,
+    /// and how long it has been waiting. See `fail_stuck_async_ops`.
+    #[wasm_bindgen]
+    pub fn get_async_ops_json(&self) -> String {
+        let now = self.system.hal().now_nanos();
+        let ops: Vec<_> = self
+            .system
+            .pending_async_ops()
+            .iter()
+            .map(|op| {
+                let name = self
+                    .system
+                    .get_process(ProcessId(op.pid))
+                    .map(|proc| proc.name.clone());
+                serde_json::json!({
+                    "channel": format!("{:?}", op.channel),
+                    "request_id": op.request_id,
+                    "pid": op.pid,
+                    "process": name,
+                    "age_ms": op.age_nanos(now) / 1_000_000
+                })
+            })
+            .collect();
+        serde_json::to_string(&ops).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Get per-process network traffic as JSON for dashboard
+    ///
+    /// Counters are those last published by the NetworkService, so they
+    /// cover only requests it mediated. Each entry has the PID, process name
+    /// (null once it has exited), requests forwarded and refused, and
+    /// payload bytes each way. `cache` holds the response cache counters.
+    #[wasm_bindgen]
+    pub fn get_network_stats_json(&self) -> String {
+        let apps: Vec<_> = self
+            .network_stats
+            .apps
+            .iter()
+            .map(|(pid, traffic)| {
+                let name = self
+                    .system
+                    .get_process(ProcessId(*pid as u64))
+                    .map(|proc| proc.name.clone());
+                serde_json::json!({
+                    "pid": pid,
+                    "process": name,
+                    "requests": traffic.requests,
+                    "denied": traffic.denied,
+                    "bytes_sent": traffic.bytes_sent,
+                    "bytes_received": traffic.bytes_received
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "offline": self.network_stats.offline,
+            "apps": apps,
+            "cache": self.network_stats.cache
+        })
+        .to_string()
+    }
+
+    /// Get VFS pending operation counters as JSON for dashboard
+    ///
+    /// Counters are those last published by the VfsService: operations
+    /// pending, timed out, dropped past their deadline and aborted as stuck,
+    /// and the orphaned records and bytes garbage collection reclaimed.
+    #[wasm_bindgen]
+    pub fn get_vfs_stats_json(&self) -> String {
+        serde_json::to_string(&self.vfs_stats).unwrap_or_else(|_| "{}".to_string())
+    }
+
     /// Get recent IPC traffic as JSON for dashboard
     ///
     /// NOTE: IPC traffic logging has been moved out of the kernel as part of
@@ -303,7 +393,14 @@ impl Supervisor {
             "endpoint_count": m.endpoint_count,
             "total_pending_messages": m.total_pending_messages,
             "total_ipc_messages": m.total_ipc_messages,
-            "uptime_ns": m.uptime_ns
+            "uptime_ns": m.uptime_ns,
+            "message_pool": {
+                "hits": m.message_pool.hits,
+                "misses": m.message_pool.misses,
+                "recycled": m.message_pool.recycled,
+                "discarded": m.message_pool.discarded,
+                "hit_rate": m.message_pool.hit_rate()
+            }
         }))
         .unwrap_or_else(|_| "{}".to_string())
     }
@@ -34,7 +34,8 @@
 //!
 //! 1. Console input → Direct IPC to terminal OR routed via Init
 //! 2. Capability revocation → Routed to PermissionService
-//! 3. IPC delivery → Routed via Init
+//! 3. App data deletion → Routed to PermissionService (signed on to VFS)
+//! 4. IPC delivery → Routed via Init
 //!
 //! This ensures:
 //!
@@ -47,23 +48,38 @@
 //! - Forge sender identity in syscalls (identity from trusted execution context)
 //! - Bypass capability checks (uses standard ipc_send)
 
+mod automation;
 mod axiom_sync;
 mod boot;
+mod chaos;
+mod completions;
 mod console;
+mod crashes;
 mod debug_dispatch;
+mod flags;
 mod ipc;
 mod metrics;
 mod network;
+mod notify;
+mod permission_prompt;
+mod print;
+mod shm;
 mod spawn;
+mod speech;
 mod storage;
+mod storage_batch;
 mod syscall_dispatch;
+mod thumbnail;
 mod worker_events;
 
 use std::collections::HashMap;
 
 use wasm_bindgen::prelude::*;
+use zos_flags::FeatureFlags;
 use zos_hal::HAL;
 use zos_kernel::{ProcessId, System};
+use zos_network::policy::NetworkStats;
+use zos_vfs::ipc::VfsStats;
 
 use crate::constants::SERVICE_INPUT_SLOT;
 use crate::hal::WasmHal;
@@ -71,7 +87,9 @@ use crate::pingpong::PingPongTestState;
 use crate::util::log;
 use crate::worker::WasmProcessHandle;
 
+use chaos::ChaosState;
 use spawn::SpawnTracker;
+use storage_batch::StorageOutbox;
 
 // Note: Console I/O uses capability-checked IPC.
 // - Console output: Uses SYS_CONSOLE_WRITE syscall (supervisor delivers to UI)
@@ -141,6 +159,38 @@ pub struct Supervisor {
     /// Tracks pending spawn operations for timeout detection and state correlation.
     /// Used during transitional direct-spawn and required for future Init-driven spawn.
     spawn_tracker: SpawnTracker,
+
+    // ==========================================================================
+    // Feature flags
+    // ==========================================================================
+    /// Latest system-wide snapshot published by the FeatureFlagService
+    feature_flags: FeatureFlags,
+    /// Fault injection state (delayed storage results, kill cadence)
+    chaos: ChaosState,
+    /// Storage results waiting to be delivered on the next poll tick
+    storage_outbox: StorageOutbox,
+    /// Runs desktop automation scripts in the DesktopController
+    desktop_script_callback: Option<js_sys::Function>,
+    /// Speaks utterances from the SpeechService (`speechSynthesis` in JS)
+    speech_callback: Option<js_sys::Function>,
+    /// Shows print jobs from the PrintService in the browser's print dialog
+    print_callback: Option<js_sys::Function>,
+    /// Asks the user about capability requests PermissionService prompts for
+    permission_prompt_callback: Option<js_sys::Function>,
+    /// Decodes images into thumbnails for the ThumbnailService
+    thumbnail_callback: Option<js_sys::Function>,
+    /// Shows notifications posted by services
+    notification_callback: Option<js_sys::Function>,
+    /// Told about service and app crashes reported by Init
+    crash_callback: Option<js_sys::Function>,
+    /// Last crash of every service and app that has crashed
+    crash_reports: crashes::CrashReports,
+    /// Bytes of the live shared memory regions, by region
+    shm_buffers: shm::ShmBuffers,
+    /// Latest traffic counters published by the NetworkService
+    network_stats: NetworkStats,
+    /// Latest pending operation counters published by the VfsService
+    vfs_stats: VfsStats,
 }
 
 #[wasm_bindgen]
@@ -175,24 +225,51 @@ impl Supervisor {
             terminal_endpoint_slots: HashMap::new(),
             // Spawn tracking for async operations
             spawn_tracker: SpawnTracker::new(),
+            // Built-in defaults until the flags service publishes a snapshot
+            feature_flags: FeatureFlags::default(),
+            chaos: ChaosState::default(),
+            storage_outbox: StorageOutbox::default(),
+            desktop_script_callback: None,
+            speech_callback: None,
+            print_callback: None,
+            permission_prompt_callback: None,
+            thumbnail_callback: None,
+            notification_callback: None,
+            crash_callback: None,
+            crash_reports: crashes::CrashReports::new(),
+            shm_buffers: shm::ShmBuffers::new(),
+            network_stats: NetworkStats::default(),
+            vfs_stats: VfsStats::default(),
         }
     }
 
-    /// Set callback for spawning processes (JS will fetch WASM and call complete_spawn)
+    /// Set callback for spawning processes (JS will fetch WASM and call
+    /// complete_requested_spawn with the request ID it is given)
     #[wasm_bindgen]
     pub fn set_spawn_callback(&mut self, callback: js_sys::Function) {
         self.spawn_callback = Some(callback);
     }
 
     /// Request JS to spawn a process (fetch WASM binary)
-    pub(crate) fn request_spawn(&mut self, proc_type: &str, name: &str) {
+    ///
+    /// The spawn is tracked from here on, with the process group the new
+    /// process joins, if any.
+    pub(crate) fn request_spawn(&mut self, proc_type: &str, name: &str, group: Option<ProcessId>) {
+        let current_time = self.system.hal().wallclock_ms();
+        let request_id =
+            self.spawn_tracker
+                .start_spawn_in_group(name, group.map(|pgid| pgid.0), current_time);
         if let Some(ref callback) = self.spawn_callback {
             let this = JsValue::null();
             let type_arg = JsValue::from_str(proc_type);
             let name_arg = JsValue::from_str(name);
-            let _ = callback.call2(&this, &type_arg, &name_arg);
+            let id_arg = JsValue::from(request_id);
+            let _ = callback.call3(&this, &type_arg, &name_arg, &id_arg);
         } else {
             self.write_console("Error: Spawn callback not set\n");
+            if let Some(spawn) = self.spawn_tracker.get_mut(request_id) {
+                spawn.fail("Spawn callback not set".into());
+            }
         }
     }
 
@@ -306,6 +383,58 @@ impl Supervisor {
         }
     }
 
+    /// Delete an app's data for a user via PermissionService
+    ///
+    /// PermissionService forwards the deletion to VfsService as a request
+    /// signed with its service key, so VFS can attribute and audit it.
+    /// `user_id` is the user ID as a hex string.
+    ///
+    /// Returns true if the request was sent to PermissionService.
+    #[wasm_bindgen]
+    pub fn delete_app_data(&mut self, user_id: &str, app_id: &str) -> bool {
+        use zos_ipc::supervisor::MSG_SUPERVISOR_DELETE_APP_DATA;
+
+        let Ok(user_id) = u128::from_str_radix(user_id, 16) else {
+            log(&format!(
+                "[supervisor] Invalid user ID for app data deletion: {}",
+                user_id
+            ));
+            return false;
+        };
+        let Some(ps_slot) = self.ps_endpoint_slot else {
+            log(&format!(
+                "[supervisor] Cannot delete data of {}: PS not initialized",
+                app_id
+            ));
+            return false;
+        };
+
+        // Build message for PS: [user_id: u128, app_id: [u8]]
+        let mut payload = Vec::with_capacity(16 + app_id.len());
+        payload.extend_from_slice(&user_id.to_le_bytes());
+        payload.extend_from_slice(app_id.as_bytes());
+
+        match self
+            .system
+            .ipc_send(ProcessId(0), ps_slot, MSG_SUPERVISOR_DELETE_APP_DATA, payload)
+        {
+            Ok(()) => {
+                log(&format!(
+                    "[supervisor] Sent delete app data request to PS for {}",
+                    app_id
+                ));
+                true
+            }
+            Err(e) => {
+                log(&format!(
+                    "[supervisor] Failed to send delete app data request to PS: {:?}",
+                    e
+                ));
+                false
+            }
+        }
+    }
+
     /// Progress the ping-pong test state machine
     fn progress_pingpong_test(&mut self) {
         use crate::pingpong::{progress_pingpong_test, PingPongContext};
@@ -339,6 +468,11 @@ impl Supervisor {
     /// Poll and process syscalls from Worker SharedArrayBuffer mailboxes
     #[wasm_bindgen]
     pub fn poll_syscalls(&mut self) -> usize {
+        // Async results that completed since the last tick; storage results
+        // go out as one message per process
+        self.deliver_async_completions();
+        self.flush_storage_results();
+
         let pending = self.system.hal().poll_syscalls();
         let count = pending.len();
 
@@ -351,6 +485,9 @@ impl Supervisor {
             })
             .collect();
 
+        // One commit for all mutations made by this tick's timers and syscalls
+        self.system.begin_commit_batch();
+        self.system.fire_timers();
         for (syscall_info, data) in syscalls {
             let pid = ProcessId(syscall_info.pid);
 
@@ -365,6 +502,7 @@ impl Supervisor {
             // Write result and wake worker
             self.system.hal().complete_syscall(syscall_info.pid, result);
         }
+        self.system.end_commit_batch();
 
         // Progress the ping-pong test state machine if running
         self.progress_pingpong_test();
@@ -372,6 +510,9 @@ impl Supervisor {
         // Check for spawn timeouts periodically
         self.check_spawn_timeouts();
 
+        // Release delayed storage results and run kill checks (chaos mode only)
+        self.chaos_tick();
+
         count
     }
 
@@ -401,14 +542,15 @@ impl Supervisor {
             return;
         }
 
-        self.kill_process_via_init(process_id);
+        self.kill_process_via_init(process_id, false);
     }
 
     /// Route a kill request through Init via MSG_SUPERVISOR_KILL_PROCESS.
     ///
     /// Init receives the request and invokes SYS_KILL syscall, which is
-    /// properly logged via SysLog.
-    fn kill_process_via_init(&mut self, target_pid: ProcessId) {
+    /// properly logged via SysLog. `crashed` tells Init the process died on
+    /// its own, so the exit counts towards its crash loop detection.
+    fn kill_process_via_init(&mut self, target_pid: ProcessId, crashed: bool) {
         let init_slot = match self.init_endpoint_slot {
             Some(slot) => slot,
             None => {
@@ -419,8 +561,9 @@ impl Supervisor {
 
         use zos_ipc::supervisor::MSG_SUPERVISOR_KILL_PROCESS;
 
-        // Build message for Init: [target_pid: u32]
-        let payload = (target_pid.0 as u32).to_le_bytes().to_vec();
+        // Build message for Init: [target_pid: u32, crashed: u8]
+        let mut payload = (target_pid.0 as u32).to_le_bytes().to_vec();
+        payload.push(crashed as u8);
         let supervisor_pid = ProcessId(0);
 
         match self.system.ipc_send(
@@ -446,6 +589,43 @@ impl Supervisor {
         }
     }
 
+    /// Restart a service by PID.
+    ///
+    /// Init drains the service (pending operations finish and its state is
+    /// checkpointed) before killing it and spawning it again, so in-flight
+    /// writes are not dropped. Kill and spawn then follow the usual
+    /// INIT:KILL_OK and INIT:SPAWN flows.
+    #[wasm_bindgen]
+    pub fn restart_service(&mut self, pid: u64) {
+        let init_slot = match self.init_endpoint_slot {
+            Some(slot) => slot,
+            None => {
+                log("[supervisor] Cannot restart service: no Init capability");
+                return;
+            }
+        };
+
+        use zos_ipc::supervisor::MSG_SUPERVISOR_RESTART_SERVICE;
+
+        // Build message for Init: [service_pid: u32]
+        let payload = (pid as u32).to_le_bytes().to_vec();
+        match self.system.ipc_send(
+            ProcessId(0),
+            init_slot,
+            MSG_SUPERVISOR_RESTART_SERVICE,
+            payload,
+        ) {
+            Ok(()) => log(&format!(
+                "[supervisor] Sent restart request for PID {} to Init (draining)",
+                pid
+            )),
+            Err(e) => log(&format!(
+                "[supervisor] Failed to route restart via Init: {:?}",
+                e
+            )),
+        }
+    }
+
     /// Directly kill a process via kernel call (bootstrap-only exception).
     ///
     /// # Invariant Exception
@@ -512,7 +692,7 @@ impl Supervisor {
         for pid in &pids {
             if pid.0 != 1 {
                 self.cleanup_process_state(pid.0);
-                self.kill_process_via_init(*pid);
+                self.kill_process_via_init(*pid, false);
             }
         }
 
@@ -577,6 +757,12 @@ impl Supervisor {
         self.notify_storage_error_internal(request_id, error)
     }
 
+    /// Called by JavaScript when a storage request was aborted after storage_cancel.
+    #[wasm_bindgen]
+    pub fn notify_storage_cancelled(&mut self, request_id: u32) {
+        self.notify_storage_cancelled_internal(request_id)
+    }
+
     // ==========================================================================
     // Wasm-bindgen wrappers for keystore callbacks (ZosKeystore)
     // ==========================================================================
@@ -2,8 +2,9 @@
 //!
 //! This module handles the integration between JavaScript network operations
 //! (fetch API) and WASM processes. The supervisor receives notifications from
-//! JavaScript when network operations complete and delivers the results to the
-//! requesting processes via IPC through Init.
+//! JavaScript when network operations complete and queues them as HAL
+//! completions; the next poll tick delivers them to the requesting processes
+//! via IPC through Init.
 //!
 //! # Safety Invariants
 //!
@@ -23,12 +24,19 @@
 //! - Result delivered to wrong PID (both request_id and PID verification required)
 //! - Silent failures without logging (all failures must be logged)
 //! - Raw JavaScript error details leaked to process (sanitize to "Internal error")
+//!
+//! The NetworkService also publishes its per-process traffic counters as
+//! `NET:STATS:{hex_json}`; the latest snapshot backs
+//! `get_network_stats_json` in the metrics API. Its responses to clients
+//! arrive as `NET:RESPONSE:` and are routed like VFS responses.
 
 use wasm_bindgen::prelude::*;
-use zos_hal::HAL;
+use zos_hal::{AsyncChannel, AsyncOutcome, HAL};
+use zos_kernel::ProcessId;
+use zos_network::policy::NetworkStats;
 
 use crate::constants::SERVICE_INPUT_SLOT;
-use crate::util::log;
+use crate::util::{hex_to_bytes, log};
 
 impl super::Supervisor {
     /// Internal handler for network result.
@@ -43,8 +51,8 @@ impl super::Supervisor {
             request_id, pid
         ));
 
-        // Verify the PID matches and remove from pending
-        let expected_pid = match self.system.hal().take_network_request_pid(request_id) {
+        // Verify the PID matches before completing the request
+        let expected_pid = match self.system.hal().get_network_request_pid(request_id) {
             Some(p) => p,
             None => {
                 log(&format!(
@@ -60,6 +68,7 @@ impl super::Supervisor {
                 "[supervisor] Network request PID mismatch: expected {}, got {}",
                 expected_pid, pid
             ));
+            self.system.hal().take_network_request_pid(request_id);
             return;
         }
 
@@ -72,22 +81,75 @@ impl super::Supervisor {
             }
         };
 
-        // Build MSG_NET_RESULT payload
-        // Format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
-        let result_bytes = result_json.as_bytes();
-        let mut payload = Vec::with_capacity(9 + result_bytes.len());
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(0); // NET_OK - the actual result status is in the JSON
-        payload.extend_from_slice(&(result_bytes.len() as u32).to_le_bytes());
-        payload.extend_from_slice(result_bytes);
-
-        // Deliver to requesting process via Init
-        self.deliver_network_result(pid, &payload);
+        // Encoded as MSG_NET_RESULT when the completion is delivered
+        self.system.hal().complete_async(
+            AsyncChannel::Network,
+            request_id,
+            AsyncOutcome::Response(result_json.into_bytes()),
+        );
     }
 
     /// Deliver a network result to a process via IPC through Init.
-    fn deliver_network_result(&mut self, pid: u64, payload: &[u8]) {
+    pub(super) fn deliver_network_result(&mut self, pid: u64, payload: &[u8]) {
         // Route through Init for capability-checked delivery
         self.route_ipc_via_init(pid, SERVICE_INPUT_SLOT, zos_ipc::net::MSG_NET_RESULT, payload);
     }
+
+    /// Handle NET:RESPONSE: from the NetworkService.
+    ///
+    /// Format: {to_pid}:{tag_hex}:{hex_data}
+    /// Only MSG_NET_RESPONSE and MSG_NET_JOB_RESULT are routed, and only
+    /// from the NetworkService, so it cannot forge other messages.
+    pub(super) fn handle_debug_net_response(&mut self, pid: ProcessId, rest: &str) {
+        if self.find_service_pid("network") != Some(pid) {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring NET:RESPONSE from PID {}",
+                pid.0
+            ));
+            return;
+        }
+
+        let parts: Vec<&str> = rest.splitn(3, ':').collect();
+        let parsed = match parts.as_slice() {
+            [to_pid, tag, hex] => to_pid
+                .parse::<u32>()
+                .ok()
+                .zip(u32::from_str_radix(tag, 16).ok())
+                .zip(hex_to_bytes(hex).ok()),
+            _ => None,
+        };
+        let Some(((to_pid, tag), data)) = parsed else {
+            log(&format!("[supervisor] Malformed NET:RESPONSE: {}", rest));
+            return;
+        };
+        if tag != zos_ipc::net::MSG_NET_RESPONSE && tag != zos_ipc::net::MSG_NET_JOB_RESULT {
+            log(&format!(
+                "[supervisor] SECURITY: NET:RESPONSE with tag 0x{:x} refused",
+                tag
+            ));
+            return;
+        }
+
+        self.route_ipc_via_init(to_pid as u64, SERVICE_INPUT_SLOT, tag, &data);
+    }
+
+    /// Handle NET:STATS:{hex_json} from the NetworkService.
+    ///
+    /// Snapshots from any other process are ignored.
+    pub(super) fn handle_debug_net_stats(&mut self, pid: ProcessId, hex_data: &str) {
+        if self.find_service_pid("network") != Some(pid) {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring NET:STATS from PID {}",
+                pid.0
+            ));
+            return;
+        }
+        match hex_to_bytes(hex_data)
+            .ok()
+            .and_then(|bytes| NetworkStats::from_json(&bytes))
+        {
+            Some(stats) => self.network_stats = stats,
+            None => log("[supervisor] Malformed NET:STATS, keeping previous counters"),
+        }
+    }
 }
@@ -0,0 +1,66 @@
+//! Desktop notifications
+//!
+//! Services post notifications as `NOTIFY:SHOW:{hex_json}`; the supervisor
+//! checks the sender and passes the JSON (`{"title", "body", "source",
+//! "tag"}`) to the JS notification callback, which shows it. The
+//! TimeService uses this for alarms, whose apps may not be running, and the
+//! MessagingService for incoming messages.
+
+use wasm_bindgen::prelude::*;
+use zos_kernel::ProcessId;
+
+use super::Supervisor;
+use crate::util::{hex_to_bytes, log};
+
+/// Services allowed to post notifications.
+const NOTIFICATION_SOURCES: &[&str] = &["time", "messaging"];
+
+#[wasm_bindgen]
+impl Supervisor {
+    /// Register the callback that shows notifications.
+    ///
+    /// Called as `callback(notificationJson)`.
+    #[wasm_bindgen]
+    pub fn set_notification_callback(&mut self, callback: js_sys::Function) {
+        self.notification_callback = Some(callback);
+        log("[supervisor] Notification callback registered");
+    }
+}
+
+impl Supervisor {
+    /// Handle NOTIFY:SHOW:{hex_json} from a service.
+    pub(super) fn handle_debug_notify_show(&mut self, pid: ProcessId, hex_data: &str) {
+        let allowed = NOTIFICATION_SOURCES
+            .iter()
+            .any(|name| self.find_service_pid(name) == Some(pid));
+        if !allowed {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring NOTIFY:SHOW from PID {}",
+                pid.0
+            ));
+            return;
+        }
+        let Some(json) = hex_to_bytes(hex_data)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .filter(|json| serde_json::from_str::<serde_json::Value>(json).is_ok())
+        else {
+            log("[supervisor] Malformed NOTIFY:SHOW payload");
+            return;
+        };
+
+        let Some(callback) = &self.notification_callback else {
+            log(&format!(
+                "[supervisor] Notification (no callback): {}",
+                json
+            ));
+            return;
+        };
+        if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from_str(&json)) {
+            log(&format!(
+                "[supervisor] Notification callback failed: {:?}",
+                e
+            ));
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Permission prompts - asking the user before PermissionService grants
+//!
+//! Some capabilities, like a Debugger capability, are only granted once the
+//! user approves. PermissionService sends the request as
+//! `PERMSVC:PROMPT:{hex_json}`; the supervisor adds the requesting app's
+//! name, passes it to the JS prompt callback and relays the answer back with
+//! `MSG_SUPERVISOR_PROMPT_RESPONSE` once `permission_prompt_answered` is
+//! called.
+
+use wasm_bindgen::prelude::*;
+use zos_ipc::supervisor::MSG_SUPERVISOR_PROMPT_RESPONSE;
+use zos_kernel::ProcessId;
+
+use super::Supervisor;
+use crate::util::{hex_to_bytes, log};
+
+#[wasm_bindgen]
+impl Supervisor {
+    /// Register the callback that asks the user about a capability request.
+    ///
+    /// Called as `callback(promptJson)`, where the prompt JSON is
+    /// `{"id", "pid", "app", "object_type", "object_name", "permissions",
+    /// "reason"}`; pass its `id` to `permission_prompt_answered` once the
+    /// user decides.
+    #[wasm_bindgen]
+    pub fn set_permission_prompt_callback(&mut self, callback: js_sys::Function) {
+        self.permission_prompt_callback = Some(callback);
+        log("[supervisor] Permission prompt callback registered");
+    }
+
+    /// Relay the user's answer to a permission prompt to PermissionService.
+    ///
+    /// Returns true if the answer was sent.
+    #[wasm_bindgen]
+    pub fn permission_prompt_answered(&mut self, prompt_id: u32, granted: bool) -> bool {
+        let Some(ps_slot) = self.ps_endpoint_slot else {
+            log(&format!(
+                "[supervisor] Cannot answer prompt {}: PS not initialized",
+                prompt_id
+            ));
+            return false;
+        };
+
+        // [prompt_id: u32, granted: u8]
+        let mut payload = Vec::with_capacity(5);
+        payload.extend_from_slice(&prompt_id.to_le_bytes());
+        payload.push(granted as u8);
+
+        match self.system.ipc_send(
+            ProcessId(0),
+            ps_slot,
+            MSG_SUPERVISOR_PROMPT_RESPONSE,
+            payload,
+        ) {
+            Ok(()) => true,
+            Err(e) => {
+                log(&format!(
+                    "[supervisor] Failed to send prompt answer to PS: {:?}",
+                    e
+                ));
+                false
+            }
+        }
+    }
+}
+
+impl Supervisor {
+    /// Handle PERMSVC:PROMPT:{hex_json} from PermissionService.
+    pub(super) fn handle_debug_permission_prompt(&mut self, pid: ProcessId, hex_data: &str) {
+        if self.find_service_pid("permission") != Some(pid) {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring PERMSVC:PROMPT from PID {}",
+                pid.0
+            ));
+            return;
+        }
+        let Some(mut prompt) = hex_to_bytes(hex_data)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+        else {
+            log("[supervisor] Malformed PERMSVC:PROMPT payload");
+            return;
+        };
+        let id = prompt.get("id").and_then(|id| id.as_u64()).unwrap_or(0) as u32;
+        let app = prompt
+            .get("pid")
+            .and_then(|pid| pid.as_u64())
+            .and_then(|pid| self.system.get_process(ProcessId(pid)))
+            .map(|proc| proc.name.clone())
+            .unwrap_or_default();
+        prompt["app"] = serde_json::Value::String(app);
+
+        // Nobody can answer without a working callback; deny right away so
+        // the requester isn't left waiting.
+        if !self.call_permission_prompt_callback(&prompt.to_string()) {
+            self.permission_prompt_answered(id, false);
+        }
+    }
+
+    fn call_permission_prompt_callback(&self, json: &str) -> bool {
+        let Some(callback) = &self.permission_prompt_callback else {
+            log("[supervisor] No permission prompt callback, denying request");
+            return false;
+        };
+        match callback.call1(&JsValue::NULL, &JsValue::from_str(json)) {
+            Ok(_) => true,
+            Err(e) => {
+                log(&format!(
+                    "[supervisor] Permission prompt callback failed: {:?}",
+                    e
+                ));
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Print dialog - the browser end of the PrintService
+//!
+//! The PrintService queues print jobs and sends the one to show now as
+//! `PRINT:DIALOG:{hex_json}`. The supervisor passes it to the JS print
+//! callback, which lays the document out, opens the browser's print dialog
+//! and calls `print_finished` when the dialog closes so the service can
+//! record the outcome and show the next job.
+
+use wasm_bindgen::prelude::*;
+use zos_ipc::print::MSG_PRINT_DIALOG_DONE;
+use zos_kernel::ProcessId;
+
+use super::Supervisor;
+use crate::constants::SERVICE_INPUT_SLOT;
+use crate::util::{hex_to_bytes, log};
+
+#[wasm_bindgen]
+impl Supervisor {
+    /// Register the callback that shows the print dialog.
+    ///
+    /// Called as `callback(jobJson)`, where the job JSON is
+    /// `{"id", "pid", "title", "document"}`; pass its `id` to
+    /// `print_finished` when the dialog closes.
+    #[wasm_bindgen]
+    pub fn set_print_callback(&mut self, callback: js_sys::Function) {
+        self.print_callback = Some(callback);
+        log("[supervisor] Print callback registered");
+    }
+
+    /// Report how a print dialog closed: `outcome` is "printed",
+    /// "cancelled" or "failed", with `error` saying why it failed.
+    #[wasm_bindgen]
+    pub fn print_finished(&mut self, job_id: u32, outcome: &str, error: Option<String>) {
+        let Some(pid) = self.find_service_pid("print") else {
+            return;
+        };
+        let report = serde_json::json!({
+            "id": job_id,
+            "outcome": outcome,
+            "error": error,
+        })
+        .to_string();
+        self.route_ipc_via_init(
+            pid.0,
+            SERVICE_INPUT_SLOT,
+            MSG_PRINT_DIALOG_DONE,
+            report.as_bytes(),
+        );
+    }
+}
+
+impl Supervisor {
+    /// Handle PRINT:DIALOG:{hex_json} from the PrintService.
+    pub(super) fn handle_debug_print_dialog(&mut self, pid: ProcessId, hex_data: &str) {
+        if self.find_service_pid("print") != Some(pid) {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring PRINT:DIALOG from PID {}",
+                pid.0
+            ));
+            return;
+        }
+        let Some(json) = hex_to_bytes(hex_data)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+        else {
+            log("[supervisor] Malformed PRINT:DIALOG payload");
+            return;
+        };
+        let id = serde_json::from_str::<serde_json::Value>(&json)
+            .ok()
+            .and_then(|v| v.get("id").and_then(|id| id.as_u64()))
+            .unwrap_or(0) as u32;
+
+        // Without a working callback there is no dialog; fail the job right
+        // away so the service's queue keeps draining.
+        if let Err(error) = self.call_print_callback(&json) {
+            self.print_finished(id, "failed", Some(error));
+        }
+    }
+
+    fn call_print_callback(&self, json: &str) -> Result<(), String> {
+        let Some(callback) = &self.print_callback else {
+            return Err(String::from("Printing is not available"));
+        };
+        callback
+            .call1(&JsValue::NULL, &JsValue::from_str(json))
+            .map(|_| ())
+            .map_err(|e| {
+                log(&format!("[supervisor] Print callback failed: {:?}", e));
+                String::from("Print dialog could not be opened")
+            })
+    }
+}
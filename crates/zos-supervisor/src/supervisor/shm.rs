@@ -0,0 +1,80 @@
+//! Shared memory
+//!
+//! The kernel tracks shared memory regions and checks every access, but a
+//! region's bytes live here: one `SharedArrayBuffer` per region, created on
+//! first use. Once the kernel has allowed a `SYS_SHM_FLUSH` or
+//! `SYS_SHM_FETCH`, the supervisor copies between the worker's memory and
+//! the region's buffer. Data moved this way never goes through a mailbox
+//! or an IPC message, so it is neither size-limited nor re-encoded.
+//!
+//! Buffers of regions the kernel has reaped are dropped after each shared
+//! memory syscall.
+
+use std::collections::HashMap;
+
+use zos_kernel::{ProcessId, ShmAccess};
+
+use super::Supervisor;
+use crate::constants::{SYS_SHM_FETCH, SYS_SHM_FLUSH};
+use crate::util::log;
+
+/// Bytes of the live shared memory regions, by region.
+pub(crate) type ShmBuffers = HashMap<u64, js_sys::SharedArrayBuffer>;
+
+impl Supervisor {
+    /// Handle a shared memory syscall (SYS_SHM_CREATE to SYS_SHM_FETCH).
+    ///
+    /// The kernel validates and logs it like any syscall; flush and fetch
+    /// are then carried out here.
+    pub(super) fn handle_sys_shm(
+        &mut self,
+        pid: ProcessId,
+        syscall_num: u32,
+        args: [u32; 3],
+    ) -> i32 {
+        let args4 = [args[0], args[1], args[2], 0];
+        let (mut result, _, _) = self.system.process_syscall(pid, syscall_num, args4, &[]);
+
+        if result == 0 && (syscall_num == SYS_SHM_FLUSH || syscall_num == SYS_SHM_FETCH) {
+            let flush = syscall_num == SYS_SHM_FLUSH;
+            match self
+                .system
+                .shm_access(pid, args[0], args[1], args[2], flush)
+            {
+                Ok(access) if self.copy_shm(pid, access, flush) => {}
+                _ => {
+                    // Most likely the worker's memory grew and its new buffer
+                    // has not arrived yet; the worker may retry
+                    log(&format!(
+                        "[supervisor] PID {} shared memory copy at 0x{:x} out of bounds",
+                        pid.0, args[0]
+                    ));
+                    result = zos_ipc::syscall_error::TRANSIENT as i64;
+                }
+            }
+        }
+
+        let system = &self.system;
+        self.shm_buffers
+            .retain(|&region, _| system.shm_region(region).is_some());
+
+        self.system.hal().write_syscall_data(pid.0, &[]);
+        result as i32
+    }
+
+    /// Copy an access the kernel allowed, creating the region's buffer on
+    /// first use.
+    fn copy_shm(&mut self, pid: ProcessId, access: ShmAccess, flush: bool) -> bool {
+        let Some(region) = self.system.shm_region(access.region) else {
+            return false;
+        };
+        let size = region.size;
+        let buffer = self
+            .shm_buffers
+            .entry(access.region)
+            .or_insert_with(|| js_sys::SharedArrayBuffer::new(size));
+        self.system
+            .hal()
+            .copy_shm(pid.0, access.addr, buffer, access.offset, access.len, flush)
+    }
+}
@@ -51,6 +51,7 @@
 mod capabilities;
 mod state;
 
+use state::SpawnState;
 pub use state::SpawnTracker;
 
 use wasm_bindgen::prelude::*;
@@ -103,13 +104,55 @@ impl Supervisor {
         // Start tracking this spawn operation
         let current_time = self.system.hal().wallclock_ms();
         let request_id = self.spawn_tracker.start_spawn(name, current_time);
+        self.finish_spawn(request_id, name, None, wasm_binary)
+    }
+
+    /// Complete a spawn the supervisor requested with the WASM binary.
+    ///
+    /// Called by JS from the spawn callback, with the request ID it was
+    /// given. The process joins the process group recorded with the request.
+    ///
+    /// Returns the new PID, or 0 if the spawn failed or the request is no
+    /// longer pending (e.g. it timed out).
+    #[wasm_bindgen]
+    pub fn complete_requested_spawn(&mut self, request_id: u64, wasm_binary: &[u8]) -> u64 {
+        let Some(spawn) = self
+            .spawn_tracker
+            .get_mut(request_id)
+            .filter(|spawn| spawn.state == SpawnState::WaitingForBinary)
+        else {
+            log(&format!(
+                "[supervisor] complete_requested_spawn: no pending spawn {}",
+                request_id
+            ));
+            return 0;
+        };
+        let name = spawn.proc_name.clone();
+        let group = spawn.pgid.map(ProcessId);
+        log(&format!(
+            "[supervisor] complete_requested_spawn called for '{}', {} bytes",
+            name,
+            wasm_binary.len()
+        ));
+        self.finish_spawn(request_id, &name, group, wasm_binary)
+    }
 
+    /// Register and start the process of tracked spawn `request_id`.
+    ///
+    /// Returns the new PID, or 0 if the worker failed to start.
+    fn finish_spawn(
+        &mut self,
+        request_id: u64,
+        name: &str,
+        group: Option<ProcessId>,
+        wasm_binary: &[u8],
+    ) -> u64 {
         // Mark binary as received (transitioning from WaitingForBinary to WaitingForPid)
         if let Some(spawn) = self.spawn_tracker.get_mut(request_id) {
             spawn.binary_received();
         }
 
-        let process_pid = self.register_process_for_spawn(name);
+        let process_pid = self.register_process_for_spawn(name, group);
 
         // Update spawn state with assigned PID
         if let Some(spawn) = self.spawn_tracker.get_mut(request_id) {
@@ -184,11 +227,11 @@ impl Supervisor {
         }
     }
 
-    fn register_process_for_spawn(&mut self, name: &str) -> ProcessId {
+    fn register_process_for_spawn(&mut self, name: &str, group: Option<ProcessId>) -> ProcessId {
         // TRANSITIONAL: Direct system call for process registration.
         // For Init, this is the bootstrap exception (see boot.rs).
         // For other processes, this should migrate to Init-driven spawn.
-        let process_pid = self.system.register_process(name);
+        let process_pid = self.system.register_process_in_group(name, group);
         log(&format!(
             "[supervisor] System assigned PID {} for '{}'",
             process_pid.0, name
@@ -295,7 +338,7 @@ impl Supervisor {
         }
 
         // Route non-init cleanup through Init for audit logging.
-        self.kill_process_via_init(process_pid);
+        self.kill_process_via_init(process_pid, false);
     }
 
     /// Called when a process is successfully spawned
@@ -307,7 +350,7 @@ impl Supervisor {
         if should_spawn_ponger {
             self.write_console(&format!("  Pinger spawned as PID {}\n", pid));
             self.pingpong_test = new_state;
-            self.request_spawn("pingpong", "pp_ponger");
+            self.request_spawn("pingpong", "pp_ponger", None);
         } else if matches!(new_state, pingpong::PingPongTestState::SettingUpCaps { .. }) {
             self.write_console(&format!("  Ponger spawned as PID {}\n", pid));
             self.pingpong_test = new_state;
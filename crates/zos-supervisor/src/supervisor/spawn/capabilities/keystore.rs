@@ -1,8 +1,10 @@
 //! Keystore service capability grants
 //!
-//! Handles granting Keystore endpoint capabilities to the Identity service.
-//! Unlike VFS which is granted to all processes, Keystore is only accessible
-//! by the Identity service for security isolation.
+//! Handles granting Keystore endpoint capabilities to the Identity service,
+//! PermissionService and VfsService. Unlike VFS which is granted to all
+//! processes, Keystore is only accessible by these services for security
+//! isolation. PermissionService only keeps its request signing key there,
+//! and VfsService the per-user keys file content is encrypted with.
 
 use zos_kernel::ProcessId;
 
@@ -70,6 +72,93 @@ impl Supervisor {
         }
     }
 
+    /// Grant Keystore Service endpoint capability to PermissionService
+    ///
+    /// PermissionService keeps the key it signs privileged VFS requests with
+    /// in the keystore.
+    pub(in crate::supervisor) fn grant_keystore_capability_to_permission(
+        &mut self,
+        keystore_pid: ProcessId,
+    ) {
+        let ps_pid = self
+            .system
+            .list_processes()
+            .into_iter()
+            .find(|(_, proc)| proc.name == "permission")
+            .map(|(pid, _)| pid);
+        let Some(ps_pid) = ps_pid else {
+            log("[supervisor] Cannot grant Keystore cap: PermissionService not found");
+            return;
+        };
+
+        match self.system.grant_capability(
+            keystore_pid,
+            KEYSTORE_INPUT_SLOT,
+            ps_pid,
+            zos_kernel::Permissions {
+                read: false, // Only need write (send) permission
+                write: true,
+                grant: false,
+            },
+        ) {
+            Ok(slot) => {
+                log(&format!(
+                    "[supervisor] Granted Keystore endpoint cap to PermissionService (PID {}) at slot {}",
+                    ps_pid.0, slot
+                ));
+            }
+            Err(e) => {
+                log(&format!(
+                    "[supervisor] FAILED to grant Keystore cap to PermissionService (PID {}): {:?}",
+                    ps_pid.0, e
+                ));
+            }
+        }
+    }
+
+    /// Grant Keystore Service endpoint capability to VfsService
+    ///
+    /// VfsService loads each user's content encryption key from the keystore.
+    pub(in crate::supervisor) fn grant_keystore_capability_to_vfs(
+        &mut self,
+        keystore_pid: ProcessId,
+    ) {
+        let vfs_pid = self
+            .system
+            .list_processes()
+            .into_iter()
+            .find(|(_, proc)| proc.name == "vfs")
+            .map(|(pid, _)| pid);
+        let Some(vfs_pid) = vfs_pid else {
+            log("[supervisor] Cannot grant Keystore cap: VfsService not found");
+            return;
+        };
+
+        match self.system.grant_capability(
+            keystore_pid,
+            KEYSTORE_INPUT_SLOT,
+            vfs_pid,
+            zos_kernel::Permissions {
+                read: false, // Only need write (send) permission
+                write: true,
+                grant: false,
+            },
+        ) {
+            Ok(slot) => {
+                log(&format!(
+                    "[supervisor] Granted Keystore endpoint cap to VfsService (PID {}) at slot {}",
+                    vfs_pid.0, slot
+                ));
+            }
+            Err(e) => {
+                log(&format!(
+                    "[supervisor] FAILED to grant Keystore cap to VfsService (PID {}): {:?}",
+                    vfs_pid.0, e
+                ));
+            }
+        }
+    }
+
     /// Find the Keystore service process ID (internal helper)
     pub(in crate::supervisor) fn find_keystore_service_pid(&self) -> Option<ProcessId> {
         let processes = self.system.list_processes();
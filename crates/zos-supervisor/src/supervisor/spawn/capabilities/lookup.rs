@@ -0,0 +1,90 @@
+//! Lookup-reply endpoint setup
+//!
+//! Init answers service lookups over IPC on a per-process endpoint, so a
+//! process waiting for a lookup never consumes messages meant for its
+//! input endpoint. Init owns the endpoint; the process only gets a
+//! receive-only capability to it, which is also how it finds the slot.
+
+use zos_kernel::ProcessId;
+
+use crate::supervisor::Supervisor;
+use crate::util::log;
+
+impl Supervisor {
+    /// Create the lookup-reply endpoint for a newly spawned process.
+    pub(in crate::supervisor) fn create_lookup_reply_endpoint(
+        &mut self,
+        process_pid: ProcessId,
+        name: &str,
+    ) {
+        let init_pid = ProcessId(1);
+
+        let (eid, init_slot) = match self.system.create_endpoint(init_pid) {
+            Ok(created) => created,
+            Err(e) => {
+                log(&format!(
+                    "[supervisor] Failed to create lookup-reply endpoint for {}: {:?}",
+                    name, e
+                ));
+                return;
+            }
+        };
+
+        match self.system.grant_capability(
+            init_pid,
+            init_slot,
+            process_pid,
+            zos_kernel::Permissions {
+                read: true, // Receive lookup responses
+                write: false,
+                grant: false,
+            },
+        ) {
+            Ok(slot) => {
+                log(&format!(
+                    "[supervisor] Created lookup-reply endpoint {} for {} (PID {}) at slot {}",
+                    eid.0, name, process_pid.0, slot
+                ));
+                self.notify_init_lookup_reply_cap(process_pid.0, init_slot);
+            }
+            Err(e) => {
+                log(&format!(
+                    "[supervisor] Failed to grant lookup-reply cap to {}: {:?}",
+                    name, e
+                ));
+            }
+        }
+    }
+
+    /// Notify Init which of its slots answers lookups from a process.
+    ///
+    /// Sends MSG_LOOKUP_REPLY_CAP_GRANTED to Init with [process_pid, cap_slot].
+    fn notify_init_lookup_reply_cap(&mut self, process_pid: u64, cap_slot: u32) {
+        let init_slot = match self.init_endpoint_slot {
+            Some(slot) => slot,
+            None => {
+                log("[supervisor] Cannot notify Init of lookup-reply cap: no Init capability");
+                return;
+            }
+        };
+
+        use zos_ipc::init::MSG_LOOKUP_REPLY_CAP_GRANTED;
+
+        // Build message: [process_pid: u32, cap_slot: u32]
+        let mut payload = Vec::with_capacity(8);
+        payload.extend_from_slice(&(process_pid as u32).to_le_bytes());
+        payload.extend_from_slice(&cap_slot.to_le_bytes());
+
+        if let Err(e) = self.system.ipc_send(
+            ProcessId(0),
+            init_slot,
+            MSG_LOOKUP_REPLY_CAP_GRANTED,
+            payload,
+        ) {
+            log(&format!(
+                "[supervisor] Failed to notify Init of lookup-reply cap: {:?}",
+                e
+            ));
+        }
+    }
+}
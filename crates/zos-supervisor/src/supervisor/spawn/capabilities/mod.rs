@@ -6,11 +6,13 @@
 //! - VFS service capabilities
 //! - Identity service capabilities
 //! - Keystore service capabilities
+//! - Lookup-reply endpoints
 //!
 //! This module is organized into submodules by capability domain.
 
 mod identity;
 mod keystore;
+mod lookup;
 mod supervisor;
 mod terminal;
 mod vfs;
@@ -113,6 +115,12 @@ impl Supervisor {
             }
         }
 
+        // Every process gets an endpoint for Init's lookup responses. It is
+        // created after the other grants so it never shifts their slots.
+        if name != "init" && self.init_spawned {
+            self.create_lookup_reply_endpoint(process_pid, name);
+        }
+
         // When terminal is spawned, grant Init (PID 1) capability to terminal's input endpoint
         // and grant supervisor capability for console input routing
         if name == "terminal" {
@@ -128,6 +136,10 @@ impl Supervisor {
             ));
             self.grant_vfs_capabilities_to_existing_processes(process_pid);
             self.grant_init_capability_to_service("vfs", process_pid);
+            // A restarted VFS comes up after the keystore
+            if let Some(keystore_pid) = self.find_keystore_service_pid() {
+                self.grant_keystore_capability_to_vfs(keystore_pid);
+            }
         }
 
         // When identity is spawned, grant its endpoint to processes that need identity access
@@ -149,14 +161,83 @@ impl Supervisor {
             self.grant_init_capability_to_service("time", process_pid);
         }
 
-        // When keystore is spawned, grant its endpoint to Identity service
-        // and grant Init (PID 1) capability to deliver IPC messages
+        // When update is spawned, grant Init (PID 1) capability to deliver
+        // boot health confirmation
+        if name == "update" {
+            self.grant_init_capability_to_service("update", process_pid);
+        }
+
+        // When flags is spawned, grant Init (PID 1) capability to deliver IPC messages
+        if name == "flags" {
+            self.grant_init_capability_to_service("flags", process_pid);
+        }
+
+        // When speech is spawned, grant Init (PID 1) capability to deliver
+        // speech requests and completion reports
+        if name == "speech" {
+            self.grant_init_capability_to_service("speech", process_pid);
+        }
+
+        // When events is spawned, grant Init (PID 1) capability to deliver
+        // publish and subscribe requests
+        if name == "events" {
+            self.grant_init_capability_to_service("events", process_pid);
+        }
+
+        // When calendar is spawned, grant Init (PID 1) capability to deliver
+        // calendar requests and TimeService replies
+        if name == "calendar" {
+            self.grant_init_capability_to_service("calendar", process_pid);
+        }
+
+        // When contacts is spawned, grant Init (PID 1) capability to deliver
+        // contacts requests and IdentityService replies
+        if name == "contacts" {
+            self.grant_init_capability_to_service("contacts", process_pid);
+        }
+
+        // When messaging is spawned, grant Init (PID 1) capability to deliver
+        // messaging requests and keystore, contacts and network replies
+        if name == "messaging" {
+            self.grant_init_capability_to_service("messaging", process_pid);
+        }
+
+        // When clipboard is spawned, grant Init (PID 1) capability to deliver
+        // clipboard requests, entries from other machines and messaging replies
+        if name == "clipboard" {
+            self.grant_init_capability_to_service("clipboard", process_pid);
+        }
+
+        // When print is spawned, grant Init (PID 1) capability to deliver
+        // print requests and dialog completion reports
+        if name == "print" {
+            self.grant_init_capability_to_service("print", process_pid);
+        }
+
+        // When thumbnail is spawned, grant Init (PID 1) capability to deliver
+        // thumbnail requests and decode results
+        if name == "thumbnail" {
+            self.grant_init_capability_to_service("thumbnail", process_pid);
+        }
+
+        // When commands is spawned, grant Init (PID 1) capability to deliver
+        // command registrations and palette requests
+        if name == "commands" {
+            self.grant_init_capability_to_service("commands", process_pid);
+        }
+
+        // When keystore is spawned, grant its endpoint to Identity service,
+        // PermissionService and VfsService, and grant Init (PID 1) capability
+        // to deliver IPC messages
         if name == "keystore" {
             log(&format!(
                 "[supervisor] Keystore service spawned (PID {}), setting up capabilities",
                 process_pid.0
             ));
             self.grant_keystore_capability_to_identity(process_pid);
+            self.grant_keystore_capability_to_permission(process_pid);
+            self.grant_debugger_capability_to_ps();
+            self.grant_keystore_capability_to_vfs(process_pid);
             self.grant_init_capability_to_service("keystore", process_pid);
         }
     }
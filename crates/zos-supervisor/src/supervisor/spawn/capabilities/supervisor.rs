@@ -1,7 +1,8 @@
 //! Supervisor capability grants
 //!
 //! Handles granting capabilities to the supervisor (PID 0) for Init and
-//! PermissionService endpoints.
+//! PermissionService endpoints, and the Debugger capability the supervisor
+//! gives PermissionService.
 
 use zos_kernel::ProcessId;
 
@@ -107,4 +108,40 @@ impl Supervisor {
             }
         }
     }
+
+    /// Give PermissionService the system's Debugger capability, which it
+    /// grants on to debugger apps once the user approves.
+    ///
+    /// Does nothing if PS already holds one. Called after the keystore grant
+    /// so PS finds the keystore at the slot it expects.
+    pub(in crate::supervisor) fn grant_debugger_capability_to_ps(&mut self) {
+        let Some(ps_pid) = self.find_service_pid("permission") else {
+            log("[supervisor] Cannot grant Debugger cap: PermissionService not found");
+            return;
+        };
+        let has_debugger = self.system.get_cap_space(ps_pid).is_some_and(|cspace| {
+            cspace
+                .list()
+                .iter()
+                .any(|(_, cap)| cap.object_type == zos_kernel::ObjectType::Debugger)
+        });
+        if has_debugger {
+            return;
+        }
+
+        match self.system.create_debugger_capability(ps_pid) {
+            Ok(slot) => {
+                log(&format!(
+                    "[supervisor] Granted Debugger cap to PermissionService (PID {}) at slot {}",
+                    ps_pid.0, slot
+                ));
+            }
+            Err(e) => {
+                log(&format!(
+                    "[supervisor] Failed to grant Debugger cap to PermissionService: {:?}",
+                    e
+                ));
+            }
+        }
+    }
 }
@@ -81,6 +81,10 @@ pub struct PendingSpawn {
 
     /// Timestamp when the spawn was requested (milliseconds since epoch)
     pub started_at: u64,
+
+    /// Process group to register the process into, if its requester asked
+    /// for one
+    pub pgid: Option<u64>,
 }
 
 impl PendingSpawn {
@@ -91,6 +95,7 @@ impl PendingSpawn {
             proc_name,
             state: SpawnState::WaitingForBinary,
             started_at,
+            pgid: None,
         }
     }
 
@@ -175,13 +180,24 @@ impl SpawnTracker {
 
     /// Start tracking a new spawn operation.
     pub fn start_spawn(&mut self, proc_name: &str, current_time: u64) -> u64 {
+        self.start_spawn_in_group(proc_name, None, current_time)
+    }
+
+    /// Start tracking a spawn whose process joins process group `pgid`.
+    pub fn start_spawn_in_group(
+        &mut self,
+        proc_name: &str,
+        pgid: Option<u64>,
+        current_time: u64,
+    ) -> u64 {
         let request_id = self.next_request_id;
         self.next_request_id = self.next_request_id.wrapping_add(1);
         if self.next_request_id == 0 {
             self.next_request_id = 1;
         }
 
-        let spawn = PendingSpawn::new(request_id, proc_name.to_string(), current_time);
+        let mut spawn = PendingSpawn::new(request_id, proc_name.to_string(), current_time);
+        spawn.pgid = pgid;
 
         self.pending.insert(request_id, spawn);
         request_id
@@ -327,6 +343,20 @@ mod tests {
         assert!(spawn.state.pid().is_none());
     }
 
+    #[test]
+    fn test_spawn_carries_its_group() {
+        let mut tracker = SpawnTracker::new();
+
+        // Two spawns of the same program for different requesters
+        let id1 = tracker.start_spawn_in_group("calc", Some(7), 1000);
+        let id2 = tracker.start_spawn_in_group("calc", Some(9), 1000);
+        let id3 = tracker.start_spawn("calc", 1000);
+
+        assert_eq!(tracker.get_mut(id2).unwrap().pgid, Some(9));
+        assert_eq!(tracker.get_mut(id1).unwrap().pgid, Some(7));
+        assert_eq!(tracker.get_mut(id3).unwrap().pgid, None);
+    }
+
     #[test]
     fn test_spawn_tracker_request_id_uniqueness() {
         let mut tracker = SpawnTracker::new();
@@ -0,0 +1,131 @@
+//! Speech output - the browser end of the SpeechService
+//!
+//! The SpeechService queues utterances and sends the one to speak now as
+//! `SPEECH:SPEAK:{hex_json}` (or `SPEECH:CANCEL` to cut it off). The
+//! supervisor passes them to the JS speech callback, which drives
+//! `speechSynthesis` and calls `speech_finished` when the utterance ends so
+//! the service can move on to the next one.
+//!
+//! The desktop announces focus changes through `announce`, which is a no-op
+//! unless the `accessibility.announce_focus` flag is on.
+
+use wasm_bindgen::prelude::*;
+use zos_flags::known;
+use zos_ipc::speech::{MSG_SPEAK, MSG_SPEECH_DONE};
+use zos_kernel::ProcessId;
+
+use super::Supervisor;
+use crate::constants::SERVICE_INPUT_SLOT;
+use crate::util::{hex_to_bytes, log};
+
+#[wasm_bindgen]
+impl Supervisor {
+    /// Register the callback that speaks utterances.
+    ///
+    /// Called as `callback("speak", utteranceJson)` or `callback("cancel", "")`.
+    /// The utterance JSON is `{"id", "pid", "text", "priority"}`; pass its
+    /// `id` to `speech_finished` when speaking ends or fails.
+    #[wasm_bindgen]
+    pub fn set_speech_callback(&mut self, callback: js_sys::Function) {
+        self.speech_callback = Some(callback);
+        log("[supervisor] Speech callback registered");
+    }
+
+    /// Report that an utterance finished (or failed) so the next one starts.
+    #[wasm_bindgen]
+    pub fn speech_finished(&mut self, utterance_id: u32) {
+        let Some(pid) = self.find_service_pid("speech") else {
+            return;
+        };
+        self.route_ipc_via_init(
+            pid.0,
+            SERVICE_INPUT_SLOT,
+            MSG_SPEECH_DONE,
+            &utterance_id.to_le_bytes(),
+        );
+    }
+
+    /// Speak a focus change, interrupting anything being said.
+    ///
+    /// Returns false when focus announcements are off or the SpeechService
+    /// isn't running.
+    #[wasm_bindgen]
+    pub fn announce(&mut self, text: &str) -> bool {
+        if !self.feature_flags.is_enabled(known::ANNOUNCE_FOCUS) {
+            return false;
+        }
+        let Some(pid) = self.find_service_pid("speech") else {
+            return false;
+        };
+        let request = serde_json::json!({
+            "text": text,
+            "priority": "high",
+            "interrupt": true,
+        })
+        .to_string();
+        self.route_ipc_via_init(pid.0, SERVICE_INPUT_SLOT, MSG_SPEAK, request.as_bytes());
+        true
+    }
+}
+
+impl Supervisor {
+    /// Handle SPEECH:SPEAK:{hex_json} from the SpeechService.
+    pub(super) fn handle_debug_speech_speak(&mut self, pid: ProcessId, hex_data: &str) {
+        if !self.is_speech_service(pid, "SPEECH:SPEAK") {
+            return;
+        }
+        let Some(json) = hex_to_bytes(hex_data)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+        else {
+            log("[supervisor] Malformed SPEECH:SPEAK payload");
+            return;
+        };
+        let id = serde_json::from_str::<serde_json::Value>(&json)
+            .ok()
+            .and_then(|v| v.get("id").and_then(|id| id.as_u64()))
+            .unwrap_or(0) as u32;
+
+        // Without a working callback nothing is heard; report the utterance
+        // done right away so the service's queue keeps draining.
+        if !self.call_speech_callback("speak", &json) {
+            self.speech_finished(id);
+        }
+    }
+
+    /// Handle SPEECH:CANCEL from the SpeechService.
+    pub(super) fn handle_debug_speech_cancel(&mut self, pid: ProcessId) {
+        if self.is_speech_service(pid, "SPEECH:CANCEL") {
+            self.call_speech_callback("cancel", "");
+        }
+    }
+
+    /// Only the SpeechService may drive speech output.
+    fn is_speech_service(&self, pid: ProcessId, what: &str) -> bool {
+        let ok = self.find_service_pid("speech") == Some(pid);
+        if !ok {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring {} from PID {}",
+                what, pid.0
+            ));
+        }
+        ok
+    }
+
+    fn call_speech_callback(&self, action: &str, json: &str) -> bool {
+        let Some(callback) = &self.speech_callback else {
+            return false;
+        };
+        match callback.call2(
+            &JsValue::NULL,
+            &JsValue::from_str(action),
+            &JsValue::from_str(json),
+        ) {
+            Ok(_) => true,
+            Err(e) => {
+                log(&format!("[supervisor] Speech callback failed: {:?}", e));
+                false
+            }
+        }
+    }
+}
@@ -1,16 +1,18 @@
 //! Storage System Integration
 //!
 //! This module handles the integration between IndexedDB storage (JavaScript)
-//! and the WASM processes. The supervisor receives notifications from JavaScript
-//! when IndexedDB operations complete and delivers the results to the requesting
-//! processes via IPC through Init.
+//! and the WASM processes. JavaScript reports each finished request through
+//! the notify_* callbacks; they queue it as a HAL completion, and the next
+//! poll tick delivers it to the requesting process via IPC through Init
+//! (see `deliver_async_completions`).
 //!
 //! # Safety Invariants
 //!
 //! ## Success Criteria
 //! - Storage result delivered to requesting process via Init-routed IPC
 //! - Request ID correctly correlated with original requesting PID
-//! - Payload format matches MSG_STORAGE_RESULT specification
+//! - Payload encoded with `zos_ipc::storage::StorageResult`, the envelope the
+//!   receiving services decode with
 //!
 //! ## Acceptable Partial Failures
 //! - Unknown request_id: Logged as error, no result delivered (orphaned response)
@@ -22,117 +24,60 @@
 //! - Silent failures without logging (all failures must be logged)
 //! - Payload corruption (data must match what JavaScript provided)
 
-use zos_hal::HAL;
+use zos_hal::{AsyncChannel, AsyncOutcome};
 
 use crate::constants::SERVICE_INPUT_SLOT;
 use crate::util::log;
 
 // =============================================================================
-// Storage Constants (matching zos-process::storage_result)
+// Storage Constants (matching zos-process)
 // =============================================================================
 
-/// Storage result types for MSG_STORAGE_RESULT IPC
+/// Message tags for storage and keystore result IPC
 pub(super) mod storage_const {
-    // Storage result type constants from zos-ipc
-    pub const STORAGE_READ_OK: u8 = zos_ipc::storage::result::READ_OK;
-    pub const STORAGE_WRITE_OK: u8 = zos_ipc::storage::result::WRITE_OK;
-    pub const STORAGE_NOT_FOUND: u8 = zos_ipc::storage::result::NOT_FOUND;
-    pub const STORAGE_ERROR: u8 = zos_ipc::storage::result::ERROR;
-    pub const STORAGE_LIST_OK: u8 = zos_ipc::storage::result::LIST_OK;
-    pub const STORAGE_EXISTS_OK: u8 = zos_ipc::storage::result::EXISTS_OK;
-
     /// MSG_STORAGE_RESULT tag from zos-ipc (the single source of truth)
     pub const MSG_STORAGE_RESULT: u32 = zos_ipc::storage::MSG_STORAGE_RESULT;
 
+    /// MSG_STORAGE_RESULT_BATCH tag from zos-ipc (several results for one process)
+    pub const MSG_STORAGE_RESULT_BATCH: u32 = zos_ipc::storage::MSG_STORAGE_RESULT_BATCH;
+
     /// MSG_KEYSTORE_RESULT tag from zos-ipc (for keystore-specific results)
     pub const MSG_KEYSTORE_RESULT: u32 = zos_ipc::keystore::MSG_KEYSTORE_RESULT;
 }
 
 impl super::Supervisor {
-    /// Internal handler for storage read complete.
-    pub(super) fn notify_storage_read_complete_internal(&mut self, request_id: u32, data: &[u8]) {
+    /// Queue a storage or keystore outcome reported by JavaScript.
+    fn complete_storage_request(
+        &mut self,
+        channel: AsyncChannel,
+        request_id: u32,
+        outcome: AsyncOutcome,
+    ) {
         log(&format!(
-            "[supervisor] notify_storage_read_complete: request_id={}, len={}",
+            "[supervisor] {:?} request {} finished: {}",
+            channel,
             request_id,
-            data.len()
+            outcome_summary(&outcome)
         ));
+        self.system
+            .hal()
+            .complete_async(channel, request_id, outcome);
+    }
 
-        // Look up which PID requested this
-        let pid = match self.system.hal().take_storage_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] Unknown storage request_id: {}",
-                    request_id
-                ));
-                return;
-            }
-        };
-
-        // Build MSG_STORAGE_RESULT payload
-        // Format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
-        let mut payload = Vec::with_capacity(9 + data.len());
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_READ_OK);
-        payload.extend_from_slice(&(data.len() as u32).to_le_bytes());
-        payload.extend_from_slice(data);
-
-        // Deliver to requesting process via Init
-        self.deliver_storage_result(pid, &payload);
+    /// Internal handler for storage read complete.
+    pub(super) fn notify_storage_read_complete_internal(&mut self, request_id: u32, data: &[u8]) {
+        let outcome = AsyncOutcome::ReadOk(data.to_vec());
+        self.complete_storage_request(AsyncChannel::Storage, request_id, outcome);
     }
 
     /// Internal handler for storage not found.
     pub(super) fn notify_storage_not_found_internal(&mut self, request_id: u32) {
-        log(&format!(
-            "[supervisor] notify_storage_not_found: request_id={}",
-            request_id
-        ));
-
-        let pid = match self.system.hal().take_storage_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] ERROR: Unknown storage request_id {} in not_found handler (orphaned response)",
-                    request_id
-                ));
-                return;
-            }
-        };
-
-        // Build MSG_STORAGE_RESULT payload for NOT_FOUND
-        let mut payload = Vec::with_capacity(9);
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_NOT_FOUND);
-        payload.extend_from_slice(&0u32.to_le_bytes());
-
-        self.deliver_storage_result(pid, &payload);
+        self.complete_storage_request(AsyncChannel::Storage, request_id, AsyncOutcome::NotFound);
     }
 
     /// Internal handler for storage write complete.
     pub(super) fn notify_storage_write_complete_internal(&mut self, request_id: u32) {
-        log(&format!(
-            "[supervisor] notify_storage_write_complete: request_id={}",
-            request_id
-        ));
-
-        let pid = match self.system.hal().take_storage_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] ERROR: Unknown storage request_id {} in write_complete handler (orphaned response)",
-                    request_id
-                ));
-                return;
-            }
-        };
-
-        // Build MSG_STORAGE_RESULT payload for WRITE_OK
-        let mut payload = Vec::with_capacity(9);
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_WRITE_OK);
-        payload.extend_from_slice(&0u32.to_le_bytes());
-
-        self.deliver_storage_result(pid, &payload);
+        self.complete_storage_request(AsyncChannel::Storage, request_id, AsyncOutcome::WriteOk);
     }
 
     /// Internal handler for storage list complete.
@@ -141,31 +86,8 @@ impl super::Supervisor {
         request_id: u32,
         keys_json: &str,
     ) {
-        log(&format!(
-            "[supervisor] notify_storage_list_complete: request_id={}, len={}",
-            request_id,
-            keys_json.len()
-        ));
-
-        let pid = match self.system.hal().take_storage_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] ERROR: Unknown storage request_id {} in list_complete handler (orphaned response)",
-                    request_id
-                ));
-                return;
-            }
-        };
-
-        let data = keys_json.as_bytes();
-        let mut payload = Vec::with_capacity(9 + data.len());
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_LIST_OK);
-        payload.extend_from_slice(&(data.len() as u32).to_le_bytes());
-        payload.extend_from_slice(data);
-
-        self.deliver_storage_result(pid, &payload);
+        let outcome = AsyncOutcome::ListOk(keys_json.as_bytes().to_vec());
+        self.complete_storage_request(AsyncChannel::Storage, request_id, outcome);
     }
 
     /// Internal handler for storage exists complete.
@@ -174,72 +96,40 @@ impl super::Supervisor {
         request_id: u32,
         exists: bool,
     ) {
-        log(&format!(
-            "[supervisor] notify_storage_exists_complete: request_id={}, exists={}",
-            request_id, exists
-        ));
-
-        let pid = match self.system.hal().take_storage_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] ERROR: Unknown storage request_id {} in exists_complete handler (orphaned response)",
-                    request_id
-                ));
-                return;
-            }
-        };
-
-        let mut payload = Vec::with_capacity(10);
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_EXISTS_OK);
-        payload.extend_from_slice(&1u32.to_le_bytes()); // data_len = 1
-        payload.push(if exists { 1 } else { 0 });
-
-        self.deliver_storage_result(pid, &payload);
+        let outcome = AsyncOutcome::ExistsOk(exists);
+        self.complete_storage_request(AsyncChannel::Storage, request_id, outcome);
     }
 
     /// Internal handler for storage error.
     pub(super) fn notify_storage_error_internal(&mut self, request_id: u32, error: &str) {
-        log(&format!(
-            "[supervisor] notify_storage_error: request_id={}, error={}",
-            request_id, error
-        ));
+        let outcome = AsyncOutcome::Error(error.to_string());
+        self.complete_storage_request(AsyncChannel::Storage, request_id, outcome);
+    }
 
-        let pid = match self.system.hal().take_storage_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] ERROR: Unknown storage request_id {} in error handler (orphaned response, error was: {})",
-                    request_id, error
-                ));
-                return;
-            }
-        };
-
-        let error_bytes = error.as_bytes();
-        let mut payload = Vec::with_capacity(9 + error_bytes.len());
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_ERROR);
-        payload.extend_from_slice(&(error_bytes.len() as u32).to_le_bytes());
-        payload.extend_from_slice(error_bytes);
-
-        self.deliver_storage_result(pid, &payload);
+    /// Internal handler for a storage request aborted by storage_cancel.
+    pub(super) fn notify_storage_cancelled_internal(&mut self, request_id: u32) {
+        self.complete_storage_request(AsyncChannel::Storage, request_id, AsyncOutcome::Cancelled);
     }
 
     /// Deliver a storage result to a process via IPC through Init.
+    ///
+    /// In chaos mode the result may be delayed or dropped first.
     pub(super) fn deliver_storage_result(&mut self, pid: u64, payload: &[u8]) {
-        // Route through Init for capability-checked delivery
-        // Use SERVICE_INPUT_SLOT for storage results to services.
+        if self.chaos_filter_storage_result(pid, payload) {
+            self.deliver_storage_result_now(pid, payload);
+        }
+    }
+
+    /// Deliver a storage result, bypassing fault injection.
+    ///
+    /// The result goes out with the next poll tick, batched with any other
+    /// results for the same process (see `storage_batch`).
+    pub(super) fn deliver_storage_result_now(&mut self, pid: u64, payload: &[u8]) {
+        // Routed through Init to SERVICE_INPUT_SLOT on flush.
         // Services like IdentityService and VfsService use storage syscalls and
         // receive all IPC on slot 1 via the app_main! framework.
         // Note: VFS_RESPONSE_SLOT (4) is for VFS *client* responses, not storage syscalls.
-        self.route_ipc_via_init(
-            pid,
-            SERVICE_INPUT_SLOT,
-            storage_const::MSG_STORAGE_RESULT,
-            payload,
-        );
+        self.storage_outbox.push(pid, payload);
     }
 
     /// Deliver a keystore result to a process via IPC through Init.
@@ -266,88 +156,18 @@ impl super::Supervisor {
         request_id: u32,
         data: &[u8],
     ) {
-        log(&format!(
-            "[supervisor] notify_keystore_read_complete: request_id={}, len={}",
-            request_id,
-            data.len()
-        ));
-
-        // Look up which PID requested this
-        let pid = match self.system.hal().take_keystore_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] Unknown keystore request_id: {}",
-                    request_id
-                ));
-                return;
-            }
-        };
-
-        // Build MSG_KEYSTORE_RESULT payload (same format as regular storage)
-        // Format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
-        let mut payload = Vec::with_capacity(9 + data.len());
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_READ_OK);
-        payload.extend_from_slice(&(data.len() as u32).to_le_bytes());
-        payload.extend_from_slice(data);
-
-        // Deliver to requesting process via Init (with MSG_KEYSTORE_RESULT tag)
-        self.deliver_keystore_result(pid, &payload);
+        let outcome = AsyncOutcome::ReadOk(data.to_vec());
+        self.complete_storage_request(AsyncChannel::Keystore, request_id, outcome);
     }
 
     /// Internal handler for keystore not found.
     pub(super) fn notify_keystore_not_found_internal(&mut self, request_id: u32) {
-        log(&format!(
-            "[supervisor] notify_keystore_not_found: request_id={}",
-            request_id
-        ));
-
-        let pid = match self.system.hal().take_keystore_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] ERROR: Unknown keystore request_id {} in not_found handler (orphaned response)",
-                    request_id
-                ));
-                return;
-            }
-        };
-
-        // Build MSG_KEYSTORE_RESULT payload for NOT_FOUND
-        let mut payload = Vec::with_capacity(9);
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_NOT_FOUND);
-        payload.extend_from_slice(&0u32.to_le_bytes());
-
-        self.deliver_keystore_result(pid, &payload);
+        self.complete_storage_request(AsyncChannel::Keystore, request_id, AsyncOutcome::NotFound);
     }
 
     /// Internal handler for keystore write complete.
     pub(super) fn notify_keystore_write_complete_internal(&mut self, request_id: u32) {
-        log(&format!(
-            "[supervisor] notify_keystore_write_complete: request_id={}",
-            request_id
-        ));
-
-        let pid = match self.system.hal().take_keystore_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] ERROR: Unknown keystore request_id {} in write_complete handler (orphaned response)",
-                    request_id
-                ));
-                return;
-            }
-        };
-
-        // Build MSG_KEYSTORE_RESULT payload for WRITE_OK
-        let mut payload = Vec::with_capacity(9);
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_WRITE_OK);
-        payload.extend_from_slice(&0u32.to_le_bytes());
-
-        self.deliver_keystore_result(pid, &payload);
+        self.complete_storage_request(AsyncChannel::Keystore, request_id, AsyncOutcome::WriteOk);
     }
 
     /// Internal handler for keystore list complete.
@@ -356,31 +176,8 @@ impl super::Supervisor {
         request_id: u32,
         keys_json: &str,
     ) {
-        log(&format!(
-            "[supervisor] notify_keystore_list_complete: request_id={}, len={}",
-            request_id,
-            keys_json.len()
-        ));
-
-        let pid = match self.system.hal().take_keystore_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] ERROR: Unknown keystore request_id {} in list_complete handler (orphaned response)",
-                    request_id
-                ));
-                return;
-            }
-        };
-
-        let data = keys_json.as_bytes();
-        let mut payload = Vec::with_capacity(9 + data.len());
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_LIST_OK);
-        payload.extend_from_slice(&(data.len() as u32).to_le_bytes());
-        payload.extend_from_slice(data);
-
-        self.deliver_keystore_result(pid, &payload);
+        let outcome = AsyncOutcome::ListOk(keys_json.as_bytes().to_vec());
+        self.complete_storage_request(AsyncChannel::Keystore, request_id, outcome);
     }
 
     /// Internal handler for keystore exists complete.
@@ -389,266 +186,122 @@ impl super::Supervisor {
         request_id: u32,
         exists: bool,
     ) {
-        log(&format!(
-            "[supervisor] notify_keystore_exists_complete: request_id={}, exists={}",
-            request_id, exists
-        ));
-
-        let pid = match self.system.hal().take_keystore_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] ERROR: Unknown keystore request_id {} in exists_complete handler (orphaned response)",
-                    request_id
-                ));
-                return;
-            }
-        };
-
-        let mut payload = Vec::with_capacity(10);
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_EXISTS_OK);
-        payload.extend_from_slice(&1u32.to_le_bytes()); // data_len = 1
-        payload.push(if exists { 1 } else { 0 });
-
-        self.deliver_keystore_result(pid, &payload);
+        let outcome = AsyncOutcome::ExistsOk(exists);
+        self.complete_storage_request(AsyncChannel::Keystore, request_id, outcome);
     }
 
     /// Internal handler for keystore error.
     pub(super) fn notify_keystore_error_internal(&mut self, request_id: u32, error: &str) {
-        log(&format!(
-            "[supervisor] notify_keystore_error: request_id={}, error={}",
-            request_id, error
-        ));
+        let outcome = AsyncOutcome::Error(error.to_string());
+        self.complete_storage_request(AsyncChannel::Keystore, request_id, outcome);
+    }
+}
 
-        let pid = match self.system.hal().take_keystore_request_pid(request_id) {
-            Some(p) => p,
-            None => {
-                log(&format!(
-                    "[supervisor] ERROR: Unknown keystore request_id {} in error handler (orphaned response, error was: {})",
-                    request_id, error
-                ));
-                return;
-            }
-        };
-
-        let error_bytes = error.as_bytes();
-        let mut payload = Vec::with_capacity(9 + error_bytes.len());
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_ERROR);
-        payload.extend_from_slice(&(error_bytes.len() as u32).to_le_bytes());
-        payload.extend_from_slice(error_bytes);
-
-        self.deliver_keystore_result(pid, &payload);
+/// Short description of an outcome for the log (no payload contents).
+fn outcome_summary(outcome: &AsyncOutcome) -> String {
+    match outcome {
+        AsyncOutcome::ReadOk(data) => format!("read {} bytes", data.len()),
+        AsyncOutcome::NotFound => "not found".to_string(),
+        AsyncOutcome::WriteOk => "written".to_string(),
+        AsyncOutcome::ListOk(keys_json) => format!("listed ({} bytes)", keys_json.len()),
+        AsyncOutcome::ExistsOk(exists) => format!("exists={}", exists),
+        AsyncOutcome::Error(error) => format!("error: {}", error),
+        AsyncOutcome::Cancelled => "cancelled".to_string(),
+        AsyncOutcome::Response(json) => format!("response ({} bytes)", json.len()),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::storage_const;
+    use zos_ipc::storage::{DecodeError, ResultKind, StorageResult};
 
-    // =========================================================================
-    // Payload Building Helpers (test-only)
-    // =========================================================================
-
-    /// Build a storage read result payload.
-    ///
-    /// Format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
-    fn build_storage_read_payload(request_id: u32, data: &[u8]) -> Vec<u8> {
-        let mut payload = Vec::with_capacity(9 + data.len());
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_READ_OK);
-        payload.extend_from_slice(&(data.len() as u32).to_le_bytes());
-        payload.extend_from_slice(data);
-        payload
-    }
-
-    /// Build a storage write result payload.
-    ///
-    /// Format: [request_id: u32, result_type: u8, data_len: u32]
-    fn build_storage_write_payload(request_id: u32) -> Vec<u8> {
-        let mut payload = Vec::with_capacity(9);
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_WRITE_OK);
-        payload.extend_from_slice(&0u32.to_le_bytes());
-        payload
-    }
-
-    /// Build a storage not found result payload.
-    ///
-    /// Format: [request_id: u32, result_type: u8, data_len: u32]
-    fn build_storage_not_found_payload(request_id: u32) -> Vec<u8> {
-        let mut payload = Vec::with_capacity(9);
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_NOT_FOUND);
-        payload.extend_from_slice(&0u32.to_le_bytes());
-        payload
-    }
-
-    /// Build a storage list result payload.
-    ///
-    /// Format: [request_id: u32, result_type: u8, data_len: u32, data: [u8]]
-    fn build_storage_list_payload(request_id: u32, keys_json: &str) -> Vec<u8> {
-        let data = keys_json.as_bytes();
-        let mut payload = Vec::with_capacity(9 + data.len());
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_LIST_OK);
-        payload.extend_from_slice(&(data.len() as u32).to_le_bytes());
-        payload.extend_from_slice(data);
-        payload
-    }
-
-    /// Build a storage exists result payload.
-    ///
-    /// Format: [request_id: u32, result_type: u8, data_len: u32, exists: u8]
-    fn build_storage_exists_payload(request_id: u32, exists: bool) -> Vec<u8> {
-        let mut payload = Vec::with_capacity(10);
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_EXISTS_OK);
-        payload.extend_from_slice(&1u32.to_le_bytes()); // data_len = 1
-        payload.push(if exists { 1 } else { 0 });
-        payload
-    }
-
-    /// Build a storage error result payload.
-    ///
-    /// Format: [request_id: u32, result_type: u8, data_len: u32, error: [u8]]
-    fn build_storage_error_payload(request_id: u32, error: &str) -> Vec<u8> {
-        let error_bytes = error.as_bytes();
-        let mut payload = Vec::with_capacity(9 + error_bytes.len());
-        payload.extend_from_slice(&request_id.to_le_bytes());
-        payload.push(storage_const::STORAGE_ERROR);
-        payload.extend_from_slice(&(error_bytes.len() as u32).to_le_bytes());
-        payload.extend_from_slice(error_bytes);
-        payload
-    }
-
-    /// Parse a storage result payload header.
-    ///
-    /// Returns (request_id, result_type, data_len) or None if invalid.
-    fn parse_storage_result_header(payload: &[u8]) -> Option<(u32, u8, u32)> {
-        if payload.len() < 9 {
-            return None;
-        }
-        let request_id = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
-        let result_type = payload[4];
-        let data_len = u32::from_le_bytes([payload[5], payload[6], payload[7], payload[8]]);
-        Some((request_id, result_type, data_len))
-    }
+    // The notify_* handlers encode with StorageResult; these pin the wire
+    // format the services (and ZosStorage in JavaScript) rely on.
 
     #[test]
     fn test_storage_read_payload_format() {
-        let payload = build_storage_read_payload(42, b"hello world");
-        
+        let payload = StorageResult::new(42, ResultKind::ReadOk, b"hello world").encode();
+
         // Header: 4 bytes request_id + 1 byte type + 4 bytes data_len = 9 bytes
-        assert_eq!(payload.len(), 9 + 11); // 9 header + 11 data bytes
-        
-        let (req_id, result_type, data_len) = parse_storage_result_header(&payload).unwrap();
-        assert_eq!(req_id, 42);
-        assert_eq!(result_type, storage_const::STORAGE_READ_OK);
-        assert_eq!(data_len, 11);
+        assert_eq!(payload.len(), 9 + 11);
+        assert_eq!(&payload[0..4], &42u32.to_le_bytes());
+        assert_eq!(payload[4], zos_ipc::storage::result::READ_OK);
+        assert_eq!(&payload[5..9], &11u32.to_le_bytes());
         assert_eq!(&payload[9..], b"hello world");
     }
 
     #[test]
-    fn test_storage_write_payload_format() {
-        let payload = build_storage_write_payload(123);
-        
-        assert_eq!(payload.len(), 9);
-        
-        let (req_id, result_type, data_len) = parse_storage_result_header(&payload).unwrap();
-        assert_eq!(req_id, 123);
-        assert_eq!(result_type, storage_const::STORAGE_WRITE_OK);
-        assert_eq!(data_len, 0);
-    }
-
-    #[test]
-    fn test_storage_not_found_payload_format() {
-        let payload = build_storage_not_found_payload(999);
-        
-        assert_eq!(payload.len(), 9);
-        
-        let (req_id, result_type, data_len) = parse_storage_result_header(&payload).unwrap();
-        assert_eq!(req_id, 999);
-        assert_eq!(result_type, storage_const::STORAGE_NOT_FOUND);
-        assert_eq!(data_len, 0);
+    fn test_storage_status_payload_format() {
+        for kind in [ResultKind::WriteOk, ResultKind::NotFound, ResultKind::Cancelled] {
+            let payload = StorageResult::new(123, kind, &[]).encode();
+            assert_eq!(payload.len(), 9);
+
+            let result = StorageResult::decode(&payload).unwrap();
+            assert_eq!(result.request_id, 123);
+            assert_eq!(result.kind, kind);
+            assert!(result.data.is_empty());
+        }
     }
 
     #[test]
     fn test_storage_list_payload_format() {
         let keys = r#"["key1","key2","key3"]"#;
-        let payload = build_storage_list_payload(55, keys);
-        
-        let (req_id, result_type, data_len) = parse_storage_result_header(&payload).unwrap();
-        assert_eq!(req_id, 55);
-        assert_eq!(result_type, storage_const::STORAGE_LIST_OK);
-        assert_eq!(data_len as usize, keys.len());
-        assert_eq!(&payload[9..], keys.as_bytes());
+        let payload = StorageResult::new(55, ResultKind::ListOk, keys.as_bytes()).encode();
+
+        let result = StorageResult::decode(&payload).unwrap();
+        assert_eq!(result.request_id, 55);
+        assert_eq!(result.kind, ResultKind::ListOk);
+        assert_eq!(result.data, keys.as_bytes());
     }
 
     #[test]
     fn test_storage_exists_payload_format() {
-        let payload_true = build_storage_exists_payload(1, true);
-        let payload_false = build_storage_exists_payload(2, false);
-        
+        let payload_true = StorageResult::exists(1, true).encode();
+        let payload_false = StorageResult::exists(2, false).encode();
+
         assert_eq!(payload_true.len(), 10);
         assert_eq!(payload_false.len(), 10);
-        
-        let (req_id, result_type, data_len) = parse_storage_result_header(&payload_true).unwrap();
-        assert_eq!(req_id, 1);
-        assert_eq!(result_type, storage_const::STORAGE_EXISTS_OK);
-        assert_eq!(data_len, 1);
-        assert_eq!(payload_true[9], 1); // true = 1
-        
-        let (req_id, result_type, data_len) = parse_storage_result_header(&payload_false).unwrap();
-        assert_eq!(req_id, 2);
-        assert_eq!(result_type, storage_const::STORAGE_EXISTS_OK);
-        assert_eq!(data_len, 1);
-        assert_eq!(payload_false[9], 0); // false = 0
+        assert_eq!(payload_true[9], 1);
+        assert_eq!(payload_false[9], 0);
+
+        let result = StorageResult::decode(&payload_true).unwrap();
+        assert_eq!(result.kind, ResultKind::ExistsOk);
+        assert!(result.exists_flag());
+        assert!(!StorageResult::decode(&payload_false).unwrap().exists_flag());
     }
 
     #[test]
     fn test_storage_error_payload_format() {
         let error = "File not found";
-        let payload = build_storage_error_payload(77, error);
-        
-        let (req_id, result_type, data_len) = parse_storage_result_header(&payload).unwrap();
-        assert_eq!(req_id, 77);
-        assert_eq!(result_type, storage_const::STORAGE_ERROR);
-        assert_eq!(data_len as usize, error.len());
-        assert_eq!(&payload[9..], error.as_bytes());
+        let payload = StorageResult::new(77, ResultKind::Error, error.as_bytes()).encode();
+
+        let result = StorageResult::decode(&payload).unwrap();
+        assert_eq!(result.request_id, 77);
+        assert_eq!(result.kind, ResultKind::Error);
+        assert_eq!(result.data, error.as_bytes());
     }
 
     #[test]
     fn test_parse_invalid_payload() {
-        // Too short
-        assert!(parse_storage_result_header(&[]).is_none());
-        assert!(parse_storage_result_header(&[1, 2, 3, 4, 5, 6, 7, 8]).is_none());
-        
-        // Exactly minimum valid length
-        assert!(parse_storage_result_header(&[0, 0, 0, 0, 0, 0, 0, 0, 0]).is_some());
-    }
+        assert_eq!(StorageResult::decode(&[]), Err(DecodeError::TooShort));
+        assert_eq!(
+            StorageResult::decode(&[1, 2, 3, 4, 5, 6, 7, 8]),
+            Err(DecodeError::TooShort)
+        );
 
-    #[test]
-    fn test_storage_read_empty_data() {
-        let payload = build_storage_read_payload(100, &[]);
-        
-        let (req_id, result_type, data_len) = parse_storage_result_header(&payload).unwrap();
-        assert_eq!(req_id, 100);
-        assert_eq!(result_type, storage_const::STORAGE_READ_OK);
-        assert_eq!(data_len, 0);
-        assert_eq!(payload.len(), 9); // Just the header
+        // Exactly minimum valid length
+        assert!(StorageResult::decode(&[0, 0, 0, 0, 0, 0, 0, 0, 0]).is_ok());
     }
 
     #[test]
     fn test_storage_constants_match_zos_ipc() {
         // Verify our constants match the canonical zos-ipc values
-        assert_eq!(storage_const::STORAGE_READ_OK, zos_ipc::storage::result::READ_OK);
-        assert_eq!(storage_const::STORAGE_WRITE_OK, zos_ipc::storage::result::WRITE_OK);
-        assert_eq!(storage_const::STORAGE_NOT_FOUND, zos_ipc::storage::result::NOT_FOUND);
-        assert_eq!(storage_const::STORAGE_ERROR, zos_ipc::storage::result::ERROR);
-        assert_eq!(storage_const::STORAGE_LIST_OK, zos_ipc::storage::result::LIST_OK);
-        assert_eq!(storage_const::STORAGE_EXISTS_OK, zos_ipc::storage::result::EXISTS_OK);
         assert_eq!(storage_const::MSG_STORAGE_RESULT, zos_ipc::storage::MSG_STORAGE_RESULT);
+        assert_eq!(
+            storage_const::MSG_STORAGE_RESULT_BATCH,
+            zos_ipc::storage::MSG_STORAGE_RESULT_BATCH
+        );
+        assert_eq!(storage_const::MSG_KEYSTORE_RESULT, zos_ipc::keystore::MSG_KEYSTORE_RESULT);
     }
 }
@@ -0,0 +1,162 @@
+//! Storage result batching
+//!
+//! IndexedDB completions arrive one callback at a time. Delivering each as
+//! its own MSG_STORAGE_RESULT wakes the receiving worker once per result,
+//! which adds up during a directory scan. Instead, completed results are
+//! held in a per-process outbox and flushed once per poll tick: a process
+//! with several results gets them in a single MSG_STORAGE_RESULT_BATCH.
+//!
+//! A lone result is still sent as a plain MSG_STORAGE_RESULT, and batches
+//! are split so no message exceeds the kernel's message size limit.
+//!
+//! # Safety Invariants
+//!
+//! ## Success Criteria
+//! - Every queued result is delivered exactly once, in completion order
+//! - Results are only ever batched with results for the same PID
+//!
+//! ## Forbidden States
+//! - A batch larger than what Init can route (MAX_MESSAGE_SIZE minus header)
+
+use std::collections::BTreeMap;
+
+use crate::constants::SERVICE_INPUT_SLOT;
+
+use super::storage::storage_const;
+use super::Supervisor;
+
+/// Bytes Init adds when routing a delivery:
+/// [target_pid: u32, endpoint_slot: u32, tag: u32, data_len: u16]
+const ROUTE_HEADER_LEN: usize = 14;
+
+/// Largest batch payload that still fits in one routed message.
+pub(super) const MAX_BATCH_PAYLOAD: usize = zos_kernel::MAX_MESSAGE_SIZE - ROUTE_HEADER_LEN;
+
+/// Completed storage results waiting for the next flush.
+#[derive(Default)]
+pub(super) struct StorageOutbox {
+    pending: BTreeMap<u64, Vec<Vec<u8>>>,
+}
+
+impl StorageOutbox {
+    /// Queue a result payload for `pid`.
+    pub(super) fn push(&mut self, pid: u64, payload: &[u8]) {
+        self.pending.entry(pid).or_default().push(payload.to_vec());
+    }
+
+    /// Take everything queued, packed into messages per PID.
+    ///
+    /// Returns (pid, tag, payload) in PID order, each PID's results in the
+    /// order they completed.
+    pub(super) fn drain(&mut self) -> Vec<(u64, u32, Vec<u8>)> {
+        let pending = core::mem::take(&mut self.pending);
+        let mut messages = Vec::new();
+        for (pid, results) in pending {
+            for (tag, payload) in pack_results(results, MAX_BATCH_PAYLOAD) {
+                messages.push((pid, tag, payload));
+            }
+        }
+        messages
+    }
+}
+
+/// Pack result payloads into as few messages as fit in `budget` bytes.
+///
+/// Batch format: [count: u32, (len: u32, result: [u8; len])*]
+fn pack_results(results: Vec<Vec<u8>>, budget: usize) -> Vec<(u32, Vec<u8>)> {
+    let mut messages = Vec::new();
+    let mut group: Vec<Vec<u8>> = Vec::new();
+    let mut group_len = 4;
+    for result in results {
+        let entry_len = 4 + result.len();
+        if !group.is_empty() && group_len + entry_len > budget {
+            messages.push(encode_group(core::mem::take(&mut group)));
+            group_len = 4;
+        }
+        group_len += entry_len;
+        group.push(result);
+    }
+    if !group.is_empty() {
+        messages.push(encode_group(group));
+    }
+    messages
+}
+
+fn encode_group(mut group: Vec<Vec<u8>>) -> (u32, Vec<u8>) {
+    if group.len() == 1 {
+        return (storage_const::MSG_STORAGE_RESULT, group.remove(0));
+    }
+    let len = 4 + group.iter().map(|r| 4 + r.len()).sum::<usize>();
+    let mut payload = Vec::with_capacity(len);
+    payload.extend_from_slice(&(group.len() as u32).to_le_bytes());
+    for result in &group {
+        payload.extend_from_slice(&(result.len() as u32).to_le_bytes());
+        payload.extend_from_slice(result);
+    }
+    (storage_const::MSG_STORAGE_RESULT_BATCH, payload)
+}
+
+impl Supervisor {
+    /// Deliver the storage results that completed since the last flush.
+    pub(super) fn flush_storage_results(&mut self) {
+        for (pid, tag, payload) in self.storage_outbox.drain() {
+            self.route_ipc_via_init(pid, SERVICE_INPUT_SLOT, tag, &payload);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zos_ipc::storage::{ResultKind, StorageResult};
+
+    fn result(request_id: u32, data_len: usize) -> Vec<u8> {
+        StorageResult::new(request_id, ResultKind::ReadOk, &vec![0xAB; data_len]).encode()
+    }
+
+    #[test]
+    fn test_single_result_is_not_batched() {
+        let mut outbox = StorageOutbox::default();
+        outbox.push(4, &result(1, 3));
+
+        let messages = outbox.drain();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].1, storage_const::MSG_STORAGE_RESULT);
+        assert_eq!(messages[0].2, result(1, 3));
+        assert!(outbox.drain().is_empty());
+    }
+
+    #[test]
+    fn test_results_batched_per_pid() {
+        let mut outbox = StorageOutbox::default();
+        outbox.push(4, &result(1, 0));
+        outbox.push(9, &result(2, 0));
+        outbox.push(4, &result(3, 5));
+
+        let messages = outbox.drain();
+        assert_eq!(messages.len(), 2);
+
+        let (pid, tag, payload) = &messages[0];
+        assert_eq!((*pid, *tag), (4, storage_const::MSG_STORAGE_RESULT_BATCH));
+        assert_eq!(u32::from_le_bytes(payload[0..4].try_into().unwrap()), 2);
+        let first_len = u32::from_le_bytes(payload[4..8].try_into().unwrap()) as usize;
+        assert_eq!(&payload[8..8 + first_len], &result(1, 0)[..]);
+        let rest = &payload[8 + first_len..];
+        let second_len = u32::from_le_bytes(rest[0..4].try_into().unwrap()) as usize;
+        assert_eq!(&rest[4..4 + second_len], &result(3, 5)[..]);
+
+        assert_eq!(messages[1].0, 9);
+        assert_eq!(messages[1].1, storage_const::MSG_STORAGE_RESULT);
+    }
+
+    #[test]
+    fn test_batches_split_at_budget() {
+        let results = vec![result(1, 40), result(2, 40), result(3, 40)];
+        // Room for two 53-byte entries (plus the count) but not three
+        let messages = pack_results(results, 4 + 2 * (4 + 49));
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].0, storage_const::MSG_STORAGE_RESULT_BATCH);
+        assert_eq!(messages[1].0, storage_const::MSG_STORAGE_RESULT);
+        assert_eq!(messages[1].1, result(3, 40));
+    }
+}
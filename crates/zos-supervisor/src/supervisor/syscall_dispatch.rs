@@ -10,11 +10,14 @@
 //! - SYS_DEBUG: Supervisor processes debug messages for actions like spawn requests
 //! - SYS_EXIT: Supervisor must terminate the worker after kernel state update
 //! - SYS_CONSOLE_WRITE: Supervisor delivers output to UI directly
+//! - SYS_SHM_*: Supervisor copies shared memory, which lives outside the kernel
 
 use zos_kernel::ProcessId;
 
 use super::Supervisor;
-use crate::constants::{SYS_CONSOLE_WRITE, SYS_DEBUG, SYS_EXIT, SYS_IPC_RECEIVE};
+use crate::constants::{
+    SYS_CONSOLE_WRITE, SYS_DEBUG, SYS_EXIT, SYS_IPC_RECEIVE, SYS_SHM_CREATE, SYS_SHM_FETCH,
+};
 use crate::util::log;
 
 impl Supervisor {
@@ -54,6 +57,11 @@ impl Supervisor {
             return self.handle_sys_console_write(pid, data);
         }
 
+        // Handle shared memory syscalls specially - supervisor does the copies
+        if (SYS_SHM_CREATE..=SYS_SHM_FETCH).contains(&syscall_num) {
+            return self.handle_sys_shm(pid, syscall_num, args);
+        }
+
         // Route all other syscalls through the Axiom gateway
         let args4 = [args[0], args[1], args[2], 0];
         let (result, _rich_result, response_data) =
@@ -122,7 +130,7 @@ impl Supervisor {
             self.kill_process_direct(pid);
         } else {
             // Route through Init for proper auditing
-            self.kill_process_via_init(pid);
+            self.kill_process_via_init(pid, false);
         }
 
         result as i32
@@ -0,0 +1,124 @@
+//! Thumbnail decoding - the browser end of the ThumbnailService
+//!
+//! The ThumbnailService sends images to decode as
+//! `THUMBNAIL:DECODE:{id}:{size}:{hex_data}`. The supervisor passes them to
+//! the JS thumbnail callback, which decodes and scales the image with
+//! `createImageBitmap`, encodes the result and calls `thumbnail_finished`
+//! (or `thumbnail_failed`) so the service can cache it.
+
+use wasm_bindgen::prelude::*;
+use zos_ipc::thumbnail::MSG_THUMBNAIL_DECODED;
+use zos_kernel::{ProcessId, MAX_MESSAGE_SIZE};
+
+use super::Supervisor;
+use crate::constants::SERVICE_INPUT_SLOT;
+use crate::util::{hex_to_bytes, log};
+
+/// Largest encoded thumbnail that fits in MSG_THUMBNAIL_DECODED once Init
+/// has framed it (14 bytes) and the report header (9 bytes) is added.
+pub const MAX_THUMBNAIL_BYTES: usize = MAX_MESSAGE_SIZE - 14 - 9;
+
+#[wasm_bindgen]
+impl Supervisor {
+    /// Register the callback that decodes images into thumbnails.
+    ///
+    /// Called as `callback(id, size, bytes)`, where `bytes` is a
+    /// `Uint8Array` of the image file; scale it to fit in `size` x `size`
+    /// pixels and pass `id` to `thumbnail_finished` or `thumbnail_failed`.
+    #[wasm_bindgen]
+    pub fn set_thumbnail_callback(&mut self, callback: js_sys::Function) {
+        self.thumbnail_callback = Some(callback);
+        log("[supervisor] Thumbnail callback registered");
+    }
+
+    /// Largest thumbnail `thumbnail_finished` accepts, in bytes.
+    #[wasm_bindgen]
+    pub fn thumbnail_max_bytes(&self) -> u32 {
+        MAX_THUMBNAIL_BYTES as u32
+    }
+
+    /// Report a decoded thumbnail: its dimensions and the encoded image
+    /// (PNG, JPEG or WebP) of at most `thumbnail_max_bytes` bytes.
+    #[wasm_bindgen]
+    pub fn thumbnail_finished(&mut self, id: u32, width: u16, height: u16, data: &[u8]) {
+        if data.len() > MAX_THUMBNAIL_BYTES {
+            self.thumbnail_failed(
+                id,
+                &format!("Thumbnail exceeds {} bytes", MAX_THUMBNAIL_BYTES),
+            );
+            return;
+        }
+        let mut report = Vec::with_capacity(9 + data.len());
+        report.extend_from_slice(&id.to_le_bytes());
+        report.push(1);
+        report.extend_from_slice(&width.to_le_bytes());
+        report.extend_from_slice(&height.to_le_bytes());
+        report.extend_from_slice(data);
+        self.send_thumbnail_report(&report);
+    }
+
+    /// Report that an image could not be decoded.
+    #[wasm_bindgen]
+    pub fn thumbnail_failed(&mut self, id: u32, error: &str) {
+        let mut report = Vec::with_capacity(5 + error.len());
+        report.extend_from_slice(&id.to_le_bytes());
+        report.push(0);
+        report.extend_from_slice(error.as_bytes());
+        report.truncate(MAX_THUMBNAIL_BYTES);
+        self.send_thumbnail_report(&report);
+    }
+}
+
+impl Supervisor {
+    /// Handle THUMBNAIL:DECODE:{id}:{size}:{hex_data} from the
+    /// ThumbnailService.
+    pub(super) fn handle_debug_thumbnail_decode(&mut self, pid: ProcessId, rest: &str) {
+        if self.find_service_pid("thumbnail") != Some(pid) {
+            log(&format!(
+                "[supervisor] SECURITY: ignoring THUMBNAIL:DECODE from PID {}",
+                pid.0
+            ));
+            return;
+        }
+        let mut parts = rest.splitn(3, ':');
+        let (Some(Ok(id)), Some(Ok(size)), Some(Ok(image))) = (
+            parts.next().map(str::parse::<u32>),
+            parts.next().map(str::parse::<u32>),
+            parts.next().map(hex_to_bytes),
+        ) else {
+            log("[supervisor] Malformed THUMBNAIL:DECODE payload");
+            return;
+        };
+
+        // Without a working callback nothing will report back; fail the
+        // decode right away so the service answers its requests.
+        if let Err(error) = self.call_thumbnail_callback(id, size, &image) {
+            self.thumbnail_failed(id, &error);
+        }
+    }
+
+    fn call_thumbnail_callback(&self, id: u32, size: u32, image: &[u8]) -> Result<(), String> {
+        let Some(callback) = &self.thumbnail_callback else {
+            return Err(String::from("Image decoding is not available"));
+        };
+        callback
+            .call3(
+                &JsValue::NULL,
+                &JsValue::from(id),
+                &JsValue::from(size),
+                &js_sys::Uint8Array::from(image).into(),
+            )
+            .map(|_| ())
+            .map_err(|e| {
+                log(&format!("[supervisor] Thumbnail callback failed: {:?}", e));
+                String::from("Image could not be decoded")
+            })
+    }
+
+    fn send_thumbnail_report(&mut self, report: &[u8]) {
+        let Some(pid) = self.find_service_pid("thumbnail") else {
+            return;
+        };
+        self.route_ipc_via_init(pid.0, SERVICE_INPUT_SLOT, MSG_THUMBNAIL_DECODED, report);
+    }
+}
@@ -2,24 +2,26 @@
 //!
 //! This module handles non-syscall events from Worker processes:
 //! - Ready (worker initialized)
-//! - MemoryUpdate (memory growth)
+//! - MemoryUpdate (memory growth, terminating workers over their limit)
 //! - Error (worker error)
 //! - Terminated (worker exit)
 //! - Yield (cooperative yield)
 //!
 //! Syscalls are handled separately via SharedArrayBuffer polling (poll_syscalls).
 
+use zos_hal::HAL;
 use zos_kernel::ProcessId;
 
 use crate::util::log;
-use crate::worker::{WorkerMessage, WorkerMessageType};
+use crate::worker::{WasmProcessHandle, WorkerMessage, WorkerMessageType};
 
 impl super::Supervisor {
     /// Process pending worker events (non-syscall messages).
     ///
     /// This handles worker lifecycle events that arrive via postMessage:
     /// - Ready: Worker initialized and reports memory size
-    /// - MemoryUpdate: Worker memory grew
+    /// - MemoryUpdate: Worker memory grew; a worker the kernel terminated
+    ///   for growing past its memory limit is stopped
     /// - Error: Worker encountered an error
     /// - Terminated: Worker exited
     /// - Yield: Worker yielded (no-op, just acknowledgement)
@@ -66,7 +68,7 @@ impl super::Supervisor {
                         if msg.pid == 1 {
                             self.kill_process_direct(pid);
                         } else {
-                            self.kill_process_via_init(pid);
+                            self.kill_process_via_init(pid, true);
                         }
                     }
                 }
@@ -75,7 +77,16 @@ impl super::Supervisor {
                         .hal()
                         .update_process_memory(msg.pid, memory_size);
                     let pid = ProcessId(msg.pid);
-                    self.system.update_process_memory(pid, memory_size);
+                    if self.system.update_process_memory(pid, memory_size) {
+                        // The kernel already terminated it and told Init
+                        log(&format!(
+                            "[supervisor] Worker {} over its memory limit, terminating",
+                            msg.pid
+                        ));
+                        let handle = WasmProcessHandle::new(msg.pid);
+                        let _ = self.system.hal().kill_process(&handle);
+                        self.cleanup_process_state(msg.pid);
+                    }
                 }
                 WorkerMessageType::Yield => {
                     // Worker yielded - nothing to do
@@ -1,7 +1,8 @@
 //! Async VFS Client for Event-Driven Services
 //!
 //! This module provides non-blocking VFS IPC helpers for services that cannot
-//! use the blocking `VfsClient::call()` pattern.
+//! use the blocking `VfsClient::call()` pattern. For the same requests with
+//! async/await and no hand-kept pending operations, see [`super::futures`].
 //!
 //! # Invariant Compliance
 //!
@@ -43,12 +44,24 @@ use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
 
-use crate::core::{DirEntry, Inode, VfsError};
+use crate::core::{DirEntry, Inode, UserId, VfsError};
 use crate::ipc::{
-    vfs_msg, ExistsRequest, ExistsResponse, MkdirRequest, MkdirResponse, ReadFileRequest,
-    ReadFileResponse, ReaddirRequest, ReaddirResponse, StatRequest, StatResponse, UnlinkRequest,
-    UnlinkResponse, WriteFileRequest, WriteFileResponse,
+    vfs_msg, BatchEntry, BatchOp, BatchRequest, BatchResponse, CloseRequest, CloseResponse,
+    ExistsRequest, ExistsResponse, FileHash, GetXattrRequest, GetXattrResponse, HashRequest,
+    HashResponse, LinkRequest, LinkResponse, ListXattrRequest, ListXattrResponse, MkdirRequest,
+    MkdirResponse, MountRequest, MountResponse, MountsResponse, OpenRequest, OpenResponse,
+    QuotaStatRequest, QuotaStatResponse, ReadAtRequest, ReadAtResponse, ReadFileRequest,
+    ReadFileResponse, ReaddirRequest, ReaddirResponse, RenameRequest, RenameResponse,
+    SetCallerUserRequest, SetXattrRequest, SetXattrResponse, SnapshotCreateRequest,
+    SnapshotCreateResponse, SnapshotDeleteRequest, SnapshotDeleteResponse, SnapshotInfo,
+    SnapshotListRequest, SnapshotListResponse, SnapshotRestoreRequest, SnapshotRestoreResponse,
+    StatRequest, StatResponse, TrashEntry, TrashListRequest, TrashListResponse, TrashPurgeRequest,
+    TrashPurgeResponse, TrashRestoreRequest, TrashRestoreResponse, UmountRequest, UnlinkRequest,
+    UnlinkResponse, UnwatchRequest, VfsEvent, WatchRequest, WatchResponse, WriteAtRequest,
+    WriteAtResponse, WriteFileRequest, WriteFileResponse,
 };
+use crate::mount::MountPoint;
+use crate::storage::StorageQuota;
 
 /// Default capability slot for VFS service endpoint (same as VfsClient).
 /// This is assigned by init when the process starts.
@@ -109,10 +122,45 @@ pub fn send_mkdir_request(path: &str, create_parents: bool) -> Result<(), VfsErr
 pub fn send_unlink_request(path: &str) -> Result<(), VfsError> {
     let request = UnlinkRequest {
         path: String::from(path),
+        trash: false,
     };
     send_vfs_request(vfs_msg::MSG_VFS_UNLINK, &request)
 }
 
+/// Send a VFS unlink request that moves the entry to the user's trash
+/// (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_UNLINK_RESPONSE`.
+pub fn send_trash_request(path: &str) -> Result<(), VfsError> {
+    let request = UnlinkRequest {
+        path: String::from(path),
+        trash: true,
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_UNLINK, &request)
+}
+
+/// Send a VFS rename/move request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_RENAME_RESPONSE`.
+pub fn send_rename_request(from: &str, to: &str) -> Result<(), VfsError> {
+    let request = RenameRequest {
+        from: String::from(from),
+        to: String::from(to),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_RENAME, &request)
+}
+
+/// Send a VFS hard link request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_LINK_RESPONSE`.
+pub fn send_link_request(path: &str, link_path: &str) -> Result<(), VfsError> {
+    let request = LinkRequest {
+        path: String::from(path),
+        link_path: String::from(link_path),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_LINK, &request)
+}
+
 /// Send a VFS readdir request (non-blocking).
 ///
 /// The response will arrive as a message with tag `MSG_VFS_READDIR_RESPONSE`.
@@ -133,6 +181,247 @@ pub fn send_stat_request(path: &str) -> Result<(), VfsError> {
     send_vfs_request(vfs_msg::MSG_VFS_STAT, &request)
 }
 
+/// Send a VFS hash request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_HASH_RESPONSE`.
+pub fn send_hash_request(path: &str) -> Result<(), VfsError> {
+    let request = HashRequest {
+        path: String::from(path),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_HASH, &request)
+}
+
+/// Send a VFS open request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_OPEN_RESPONSE`.
+pub fn send_open_request(path: &str, write: bool, create: bool) -> Result<(), VfsError> {
+    let request = OpenRequest {
+        path: String::from(path),
+        write,
+        create,
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_OPEN, &request)
+}
+
+/// Send a VFS read-at request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_READ_AT_RESPONSE`.
+pub fn send_read_at_request(handle: u32, offset: u64, length: u64) -> Result<(), VfsError> {
+    let request = ReadAtRequest {
+        handle,
+        offset,
+        length,
+        shm: false,
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_READ_AT, &request)
+}
+
+/// Send a VFS write-at request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_WRITE_AT_RESPONSE`.
+pub fn send_write_at_request(handle: u32, offset: u64, data: &[u8]) -> Result<(), VfsError> {
+    let request = WriteAtRequest {
+        handle,
+        offset,
+        data: data.to_vec(),
+        shm_len: None,
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_WRITE_AT, &request)
+}
+
+/// Send a VFS close request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_CLOSE_RESPONSE`.
+pub fn send_close_request(handle: u32) -> Result<(), VfsError> {
+    send_vfs_request(vfs_msg::MSG_VFS_CLOSE, &CloseRequest { handle })
+}
+
+/// Send a VFS watch request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_WATCH_RESPONSE`;
+/// changes then arrive as `MSG_VFS_EVENT` messages.
+pub fn send_watch_request(path: &str, recursive: bool) -> Result<(), VfsError> {
+    let request = WatchRequest {
+        path: String::from(path),
+        recursive,
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_WATCH, &request)
+}
+
+/// Send a VFS unwatch request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_UNWATCH_RESPONSE`.
+pub fn send_unwatch_request(id: u32) -> Result<(), VfsError> {
+    send_vfs_request(vfs_msg::MSG_VFS_UNWATCH, &UnwatchRequest { id })
+}
+
+/// Send a VFS mount request (non-blocking).
+///
+/// Only Init and the PermissionManager may mount. The response will arrive
+/// as a message with tag `MSG_VFS_MOUNT_RESPONSE`.
+pub fn send_mount_request(request: &MountRequest) -> Result<(), VfsError> {
+    send_vfs_request(vfs_msg::MSG_VFS_MOUNT, request)
+}
+
+/// Send a VFS umount request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_UMOUNT_RESPONSE`.
+pub fn send_umount_request(path: &str) -> Result<(), VfsError> {
+    let request = UmountRequest {
+        path: String::from(path),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_UMOUNT, &request)
+}
+
+/// Send a VFS mount list request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_MOUNTS_RESPONSE`.
+pub fn send_mounts_request() -> Result<(), VfsError> {
+    send_vfs_request(vfs_msg::MSG_VFS_MOUNTS, &())
+}
+
+/// Send a VFS set caller user request (non-blocking).
+///
+/// Only Init, the PermissionManager and the IdentityService may set the
+/// session user. The response will arrive as a message with tag
+/// `MSG_VFS_SET_CALLER_USER_RESPONSE`.
+pub fn send_set_caller_user_request(user_id: Option<UserId>) -> Result<(), VfsError> {
+    let request = SetCallerUserRequest { user_id };
+    send_vfs_request(vfs_msg::MSG_VFS_SET_CALLER_USER, &request)
+}
+
+/// Send a VFS quota stat request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_QUOTA_STAT_RESPONSE`.
+pub fn send_quota_stat_request(user_id: UserId) -> Result<(), VfsError> {
+    send_vfs_request(vfs_msg::MSG_VFS_QUOTA_STAT, &QuotaStatRequest { user_id })
+}
+
+/// Send a VFS batch request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_BATCH_RESPONSE`,
+/// holding one entry per operation in `ops`.
+pub fn send_batch_request(ops: Vec<BatchOp>) -> Result<(), VfsError> {
+    send_vfs_request(vfs_msg::MSG_VFS_BATCH, &BatchRequest { ops })
+}
+
+/// Send a VFS set extended attribute request (non-blocking).
+///
+/// A `value` of `None` removes the attribute. The response will arrive as a
+/// message with tag `MSG_VFS_SETXATTR_RESPONSE`.
+pub fn send_setxattr_request(path: &str, name: &str, value: Option<&str>) -> Result<(), VfsError> {
+    let request = SetXattrRequest {
+        path: String::from(path),
+        name: String::from(name),
+        value: value.map(String::from),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_SETXATTR, &request)
+}
+
+/// Send a VFS get extended attribute request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_GETXATTR_RESPONSE`.
+pub fn send_getxattr_request(path: &str, name: &str) -> Result<(), VfsError> {
+    let request = GetXattrRequest {
+        path: String::from(path),
+        name: String::from(name),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_GETXATTR, &request)
+}
+
+/// Send a VFS list extended attributes request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_LISTXATTR_RESPONSE`.
+pub fn send_listxattr_request(path: &str) -> Result<(), VfsError> {
+    let request = ListXattrRequest {
+        path: String::from(path),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_LISTXATTR, &request)
+}
+
+/// Send a VFS list trash request (non-blocking).
+///
+/// The response will arrive as a message with tag `MSG_VFS_TRASH_LIST_RESPONSE`.
+pub fn send_trash_list_request(user_id: UserId) -> Result<(), VfsError> {
+    send_vfs_request(vfs_msg::MSG_VFS_TRASH_LIST, &TrashListRequest { user_id })
+}
+
+/// Send a VFS restore from trash request (non-blocking).
+///
+/// With `to` unset the file goes back where it was unlinked from. The
+/// response will arrive as a message with tag `MSG_VFS_TRASH_RESTORE_RESPONSE`.
+pub fn send_trash_restore_request(
+    user_id: UserId,
+    id: &str,
+    to: Option<&str>,
+) -> Result<(), VfsError> {
+    let request = TrashRestoreRequest {
+        user_id,
+        id: String::from(id),
+        to: to.map(String::from),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_TRASH_RESTORE, &request)
+}
+
+/// Send a VFS purge trash request (non-blocking).
+///
+/// An `id` of `None` empties the trash. The response will arrive as a
+/// message with tag `MSG_VFS_TRASH_PURGE_RESPONSE`.
+pub fn send_trash_purge_request(user_id: UserId, id: Option<&str>) -> Result<(), VfsError> {
+    let request = TrashPurgeRequest {
+        user_id,
+        id: id.map(String::from),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_TRASH_PURGE, &request)
+}
+
+/// Send a VFS create snapshot request (non-blocking).
+///
+/// The response will arrive as a message with tag
+/// `MSG_VFS_SNAPSHOT_CREATE_RESPONSE`.
+pub fn send_snapshot_create_request(path: &str, label: Option<&str>) -> Result<(), VfsError> {
+    let request = SnapshotCreateRequest {
+        path: String::from(path),
+        label: label.map(String::from),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_SNAPSHOT_CREATE, &request)
+}
+
+/// Send a VFS list snapshots request (non-blocking).
+///
+/// The response will arrive as a message with tag
+/// `MSG_VFS_SNAPSHOT_LIST_RESPONSE`.
+pub fn send_snapshot_list_request(user_id: UserId) -> Result<(), VfsError> {
+    send_vfs_request(
+        vfs_msg::MSG_VFS_SNAPSHOT_LIST,
+        &SnapshotListRequest { user_id },
+    )
+}
+
+/// Send a VFS restore snapshot request (non-blocking).
+///
+/// The response will arrive as a message with tag
+/// `MSG_VFS_SNAPSHOT_RESTORE_RESPONSE`.
+pub fn send_snapshot_restore_request(user_id: UserId, id: &str) -> Result<(), VfsError> {
+    let request = SnapshotRestoreRequest {
+        user_id,
+        id: String::from(id),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_SNAPSHOT_RESTORE, &request)
+}
+
+/// Send a VFS delete snapshot request (non-blocking).
+///
+/// The response will arrive as a message with tag
+/// `MSG_VFS_SNAPSHOT_DELETE_RESPONSE`.
+pub fn send_snapshot_delete_request(user_id: UserId, id: &str) -> Result<(), VfsError> {
+    let request = SnapshotDeleteRequest {
+        user_id,
+        id: String::from(id),
+    };
+    send_vfs_request(vfs_msg::MSG_VFS_SNAPSHOT_DELETE, &request)
+}
+
 // =============================================================================
 // VFS Response Helpers
 // =============================================================================
@@ -149,12 +438,35 @@ pub fn is_vfs_response(tag: u32) -> bool {
             | vfs_msg::MSG_VFS_UNLINK_RESPONSE
             | vfs_msg::MSG_VFS_RENAME_RESPONSE
             | vfs_msg::MSG_VFS_COPY_RESPONSE
+            | vfs_msg::MSG_VFS_LINK_RESPONSE
             | vfs_msg::MSG_VFS_STAT_RESPONSE
             | vfs_msg::MSG_VFS_EXISTS_RESPONSE
             | vfs_msg::MSG_VFS_CHMOD_RESPONSE
             | vfs_msg::MSG_VFS_CHOWN_RESPONSE
             | vfs_msg::MSG_VFS_GET_USAGE_RESPONSE
             | vfs_msg::MSG_VFS_GET_QUOTA_RESPONSE
+            | vfs_msg::MSG_VFS_QUOTA_STAT_RESPONSE
+            | vfs_msg::MSG_VFS_OPEN_RESPONSE
+            | vfs_msg::MSG_VFS_READ_AT_RESPONSE
+            | vfs_msg::MSG_VFS_WRITE_AT_RESPONSE
+            | vfs_msg::MSG_VFS_CLOSE_RESPONSE
+            | vfs_msg::MSG_VFS_WATCH_RESPONSE
+            | vfs_msg::MSG_VFS_UNWATCH_RESPONSE
+            | vfs_msg::MSG_VFS_MOUNT_RESPONSE
+            | vfs_msg::MSG_VFS_UMOUNT_RESPONSE
+            | vfs_msg::MSG_VFS_MOUNTS_RESPONSE
+            | vfs_msg::MSG_VFS_SET_CALLER_USER_RESPONSE
+            | vfs_msg::MSG_VFS_BATCH_RESPONSE
+            | vfs_msg::MSG_VFS_SETXATTR_RESPONSE
+            | vfs_msg::MSG_VFS_GETXATTR_RESPONSE
+            | vfs_msg::MSG_VFS_LISTXATTR_RESPONSE
+            | vfs_msg::MSG_VFS_TRASH_LIST_RESPONSE
+            | vfs_msg::MSG_VFS_TRASH_RESTORE_RESPONSE
+            | vfs_msg::MSG_VFS_TRASH_PURGE_RESPONSE
+            | vfs_msg::MSG_VFS_SNAPSHOT_CREATE_RESPONSE
+            | vfs_msg::MSG_VFS_SNAPSHOT_LIST_RESPONSE
+            | vfs_msg::MSG_VFS_SNAPSHOT_RESTORE_RESPONSE
+            | vfs_msg::MSG_VFS_SNAPSHOT_DELETE_RESPONSE
     )
 }
 
@@ -208,6 +520,26 @@ pub fn parse_unlink_response(data: &[u8]) -> Result<(), String> {
     }
 }
 
+/// Parse a VFS rename response.
+///
+/// Returns `Ok(())` on success, `Err(error_message)` on failure.
+pub fn parse_rename_response(data: &[u8]) -> Result<(), String> {
+    match serde_json::from_slice::<RenameResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS hard link response.
+///
+/// Returns `Ok(())` on success, `Err(error_message)` on failure.
+pub fn parse_link_response(data: &[u8]) -> Result<(), String> {
+    match serde_json::from_slice::<LinkResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
 /// Parse a VFS readdir response.
 ///
 /// Returns `Ok(entries)` on success, `Err(error_message)` on failure.
@@ -228,6 +560,221 @@ pub fn parse_stat_response(data: &[u8]) -> Result<Inode, String> {
     }
 }
 
+/// Parse a VFS hash response.
+///
+/// Returns `Ok(hash)` on success, `Err(error_message)` on failure.
+pub fn parse_hash_response(data: &[u8]) -> Result<FileHash, String> {
+    match serde_json::from_slice::<HashResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS open response.
+///
+/// Returns `Ok(handle)` on success, `Err(error_message)` on failure.
+pub fn parse_open_response(data: &[u8]) -> Result<u32, String> {
+    match serde_json::from_slice::<OpenResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS read-at response.
+///
+/// Returns `Ok(data)` on success (short or empty at end of file),
+/// `Err(error_message)` on failure.
+pub fn parse_read_at_response(data: &[u8]) -> Result<Vec<u8>, String> {
+    match serde_json::from_slice::<ReadAtResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS write-at response.
+///
+/// Returns `Ok(size)` with the file size after the write on success,
+/// `Err(error_message)` on failure.
+pub fn parse_write_at_response(data: &[u8]) -> Result<u64, String> {
+    match serde_json::from_slice::<WriteAtResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS close response.
+///
+/// Returns `Ok(())` on success, `Err(error_message)` on failure.
+pub fn parse_close_response(data: &[u8]) -> Result<(), String> {
+    match serde_json::from_slice::<CloseResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS watch response.
+///
+/// Returns `Ok(watch_id)` on success, `Err(error_message)` on failure.
+pub fn parse_watch_response(data: &[u8]) -> Result<u32, String> {
+    match serde_json::from_slice::<WatchResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS mount or umount response.
+///
+/// Returns `Ok(())` on success, `Err(error_message)` on failure.
+pub fn parse_mount_response(data: &[u8]) -> Result<(), String> {
+    match serde_json::from_slice::<MountResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS mount list response.
+///
+/// Returns `Ok(mounts)` on success, `Err(error_message)` on failure.
+pub fn parse_mounts_response(data: &[u8]) -> Result<Vec<MountPoint>, String> {
+    match serde_json::from_slice::<MountsResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS quota stat response.
+///
+/// Returns `Ok(quota)` on success, `Err(error_message)` on failure.
+pub fn parse_quota_stat_response(data: &[u8]) -> Result<StorageQuota, String> {
+    match serde_json::from_slice::<QuotaStatResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS batch response.
+///
+/// Returns `Ok(entries)` if the batch ran, `Err(error_message)` if it was
+/// refused. Each entry holds its operation's own response, to be parsed with
+/// the matching `parse_*_response` (e.g. `parse_stat_response`).
+pub fn parse_batch_response(data: &[u8]) -> Result<Vec<BatchEntry>, String> {
+    match serde_json::from_slice::<BatchResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS set extended attribute response.
+///
+/// Returns `Ok(())` on success, `Err(error_message)` on failure.
+pub fn parse_setxattr_response(data: &[u8]) -> Result<(), String> {
+    match serde_json::from_slice::<SetXattrResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS get extended attribute response.
+///
+/// Returns `Ok(Some(value))` if the attribute is set, `Ok(None)` if not,
+/// `Err(error_message)` on failure.
+pub fn parse_getxattr_response(data: &[u8]) -> Result<Option<String>, String> {
+    match serde_json::from_slice::<GetXattrResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS list extended attributes response.
+///
+/// Returns `Ok(names)` on success, `Err(error_message)` on failure.
+pub fn parse_listxattr_response(data: &[u8]) -> Result<Vec<String>, String> {
+    match serde_json::from_slice::<ListXattrResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS list trash response.
+///
+/// Returns `Ok(entries)`, newest first, on success, `Err(error_message)` on
+/// failure.
+pub fn parse_trash_list_response(data: &[u8]) -> Result<Vec<TrashEntry>, String> {
+    match serde_json::from_slice::<TrashListResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS restore from trash response.
+///
+/// Returns `Ok(path)` with the restored file's path on success,
+/// `Err(error_message)` on failure.
+pub fn parse_trash_restore_response(data: &[u8]) -> Result<String, String> {
+    match serde_json::from_slice::<TrashRestoreResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS purge trash response.
+///
+/// Returns `Ok(count)` of files deleted on success, `Err(error_message)` on
+/// failure.
+pub fn parse_trash_purge_response(data: &[u8]) -> Result<u32, String> {
+    match serde_json::from_slice::<TrashPurgeResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS create snapshot response.
+///
+/// Returns `Ok(info)` for the new snapshot on success, `Err(error_message)`
+/// on failure.
+pub fn parse_snapshot_create_response(data: &[u8]) -> Result<SnapshotInfo, String> {
+    match serde_json::from_slice::<SnapshotCreateResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS list snapshots response.
+///
+/// Returns `Ok(snapshots)`, oldest first, on success, `Err(error_message)`
+/// on failure.
+pub fn parse_snapshot_list_response(data: &[u8]) -> Result<Vec<SnapshotInfo>, String> {
+    match serde_json::from_slice::<SnapshotListResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS restore snapshot response.
+///
+/// Returns `Ok(())` on success, `Err(error_message)` on failure.
+pub fn parse_snapshot_restore_response(data: &[u8]) -> Result<(), String> {
+    match serde_json::from_slice::<SnapshotRestoreResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a VFS delete snapshot response.
+///
+/// Returns `Ok(())` on success, `Err(error_message)` on failure.
+pub fn parse_snapshot_delete_response(data: &[u8]) -> Result<(), String> {
+    match serde_json::from_slice::<SnapshotDeleteResponse>(data) {
+        Ok(response) => response.result.map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
+/// Parse a `MSG_VFS_EVENT` change notification.
+pub fn parse_vfs_event(data: &[u8]) -> Result<VfsEvent, String> {
+    serde_json::from_slice(data).map_err(|e| format!("Parse error: {}", e))
+}
+
 // =============================================================================
 // Internal Helpers
 // =============================================================================
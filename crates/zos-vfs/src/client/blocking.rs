@@ -26,11 +26,19 @@ use alloc::vec::Vec;
 
 use crate::core::VfsError;
 use crate::ipc::{
-    vfs_msg, ExistsRequest, ExistsResponse, MkdirRequest, MkdirResponse, ReadFileRequest,
-    ReadFileResponse, ReaddirRequest, ReaddirResponse, RmdirRequest, RmdirResponse, StatRequest,
-    StatResponse, UnlinkRequest, UnlinkResponse, WriteFileRequest, WriteFileResponse,
+    vfs_msg, AttachShmRequest, AttachShmResponse, CloseRequest, CloseResponse, ExistsRequest, ExistsResponse, FileHash, GetXattrRequest,
+    GetXattrResponse, HashRequest, HashResponse, LinkRequest, LinkResponse, ListXattrRequest, ListXattrResponse, MkdirRequest, MkdirResponse, OpenRequest, OpenResponse, QuotaStatRequest, QuotaStatResponse,
+    ReadAtRequest, ReadAtResponse, ReadFileRequest, ReadFileResponse, ReaddirRequest, ReaddirResponse, RenameRequest, RenameResponse,
+    RmdirRequest, RmdirResponse, SetXattrRequest, SetXattrResponse, SnapshotCreateRequest,
+    SnapshotCreateResponse, SnapshotDeleteRequest, SnapshotDeleteResponse, SnapshotInfo,
+    SnapshotListRequest, SnapshotListResponse, SnapshotRestoreRequest, SnapshotRestoreResponse,
+    StatRequest, StatResponse, TrashEntry, TrashListRequest, TrashListResponse, TrashPurgeRequest, TrashPurgeResponse,
+    TrashRestoreRequest, TrashRestoreResponse, UnlinkRequest, UnlinkResponse,
+    UnwatchRequest, UnwatchResponse, WatchRequest, WatchResponse, WriteAtRequest,
+    WriteAtResponse, WriteFileRequest, WriteFileResponse,
 };
-use crate::core::{DirEntry, Inode};
+use crate::core::{DirEntry, Inode, UserId};
+use crate::storage::StorageQuota;
 
 /// Default capability slot for VFS service endpoint
 /// This is assigned by init when the process starts
@@ -77,9 +85,13 @@ impl VfsClient {
     #[cfg(target_arch = "wasm32")]
     pub fn connect() -> Result<Self, VfsError> {
         use zos_process::{
-            receive_blocking, send, INIT_ENDPOINT_SLOT, MSG_LOOKUP_RESPONSE, MSG_LOOKUP_SERVICE,
+            lookup_reply_slot, receive_blocking, send, INIT_ENDPOINT_SLOT, MSG_LOOKUP_RESPONSE,
+            MSG_LOOKUP_SERVICE,
         };
 
+        let reply_slot = lookup_reply_slot()
+            .ok_or_else(|| VfsError::StorageError(String::from("No lookup-reply endpoint")))?;
+
         // Send lookup request to init
         let service_name = "vfs";
         let name_bytes = service_name.as_bytes();
@@ -90,8 +102,8 @@ impl VfsClient {
         send(INIT_ENDPOINT_SLOT, MSG_LOOKUP_SERVICE, &data)
             .map_err(|e| VfsError::StorageError(alloc::format!("Lookup send failed: {}", e)))?;
 
-        // Wait for response
-        let response = receive_blocking(INIT_ENDPOINT_SLOT)
+        // Wait for init's reply on our lookup-reply endpoint
+        let response = receive_blocking(reply_slot)
             .map_err(|_| VfsError::StorageError(String::from("Receive failed")))?;
         if response.tag != MSG_LOOKUP_RESPONSE {
             return Err(VfsError::StorageError(String::from(
@@ -290,6 +302,159 @@ impl VfsClient {
         response.result
     }
 
+    /// Open a file for partial I/O.
+    ///
+    /// # Arguments
+    /// - `path`: Path to the file
+    /// - `write`: Allow `write_at` through the handle
+    /// - `create`: Create an empty file if it does not exist (requires `write`)
+    ///
+    /// # Returns
+    /// - `Ok(handle)` on success
+    /// - `Err(VfsError)` on failure
+    pub fn open(&self, path: &str, write: bool, create: bool) -> Result<u32, VfsError> {
+        let request = OpenRequest {
+            path: path.to_string(),
+            write,
+            create,
+        };
+        let response: OpenResponse = self.call(vfs_msg::MSG_VFS_OPEN, &request)?;
+        response.result
+    }
+
+    /// Read up to `length` bytes at `offset` through an open handle.
+    ///
+    /// # Returns
+    /// - `Ok(Vec<u8>)` with the bytes read; shorter than `length` at end of
+    ///   file or when the reply limit is reached, empty past the end
+    /// - `Err(VfsError)` on failure
+    pub fn read_at(&self, handle: u32, offset: u64, length: u64) -> Result<Vec<u8>, VfsError> {
+        let request = ReadAtRequest {
+            handle,
+            offset,
+            length,
+            shm: false,
+        };
+        let response: ReadAtResponse = self.call(vfs_msg::MSG_VFS_READ_AT, &request)?;
+        response.result
+    }
+
+    /// Read up to `length` bytes at `offset` into the start of the handle's
+    /// shared memory region (see `attach_shm`); fetch them from there.
+    ///
+    /// # Returns
+    /// - `Ok(len)` with the number of bytes placed in the region; shorter
+    ///   than `length` at end of file or when the region is full
+    /// - `Err(VfsError)` on failure
+    pub fn read_at_shm(&self, handle: u32, offset: u64, length: u64) -> Result<u64, VfsError> {
+        let request = ReadAtRequest {
+            handle,
+            offset,
+            length,
+            shm: true,
+        };
+        let response: ReadAtResponse = self.call(vfs_msg::MSG_VFS_READ_AT, &request)?;
+        response.result?;
+        response.shm_len.ok_or_else(|| {
+            VfsError::StorageError(String::from("Read-at did not use shared memory"))
+        })
+    }
+
+    /// Write `data` at `offset` through a handle opened for writing.
+    ///
+    /// # Returns
+    /// - `Ok(size)` with the file size after the write
+    /// - `Err(VfsError)` on failure
+    pub fn write_at(&self, handle: u32, offset: u64, data: &[u8]) -> Result<u64, VfsError> {
+        let request = WriteAtRequest {
+            handle,
+            offset,
+            data: data.to_vec(),
+            shm_len: None,
+        };
+        let response: WriteAtResponse = self.call(vfs_msg::MSG_VFS_WRITE_AT, &request)?;
+        response.result
+    }
+
+    /// Write the first `len` bytes of the handle's shared memory region
+    /// (see `attach_shm`) at `offset`; flush them there first.
+    ///
+    /// # Returns
+    /// - `Ok(size)` with the file size after the write
+    /// - `Err(VfsError)` on failure
+    pub fn write_at_shm(&self, handle: u32, offset: u64, len: u64) -> Result<u64, VfsError> {
+        let request = WriteAtRequest {
+            handle,
+            offset,
+            data: Vec::new(),
+            shm_len: Some(len),
+        };
+        let response: WriteAtResponse = self.call(vfs_msg::MSG_VFS_WRITE_AT, &request)?;
+        response.result
+    }
+
+    /// Attach a shared memory region to an open handle, for `read_at_shm`
+    /// and `write_at_shm`.
+    ///
+    /// The Memory capability in `slot` is sent to the VFS service, so map
+    /// the region here first (with `zos_process::SharedMemory::map`).
+    ///
+    /// # Returns
+    /// - `Ok(size)` with the size the service mapped, at most `size`
+    /// - `Err(VfsError)` on failure
+    pub fn attach_shm(&self, handle: u32, slot: u32, size: u32) -> Result<u32, VfsError> {
+        let request = AttachShmRequest { handle, size };
+        let response: AttachShmResponse =
+            self.call_with_caps(vfs_msg::MSG_VFS_ATTACH_SHM, &request, &[slot])?;
+        response.result
+    }
+
+    /// Close an open handle.
+    pub fn close(&self, handle: u32) -> Result<(), VfsError> {
+        let request = CloseRequest { handle };
+        let response: CloseResponse = self.call(vfs_msg::MSG_VFS_CLOSE, &request)?;
+        response.result
+    }
+
+    /// Watch a path for changes.
+    ///
+    /// Events arrive as `MSG_VFS_EVENT` messages on the process's input
+    /// endpoint, not through this client.
+    ///
+    /// # Arguments
+    /// - `path`: Existing file or directory to watch
+    /// - `recursive`: Also report changes anywhere below a directory
+    ///
+    /// # Returns
+    /// - `Ok(id)` with the watch ID on success
+    /// - `Err(VfsError)` on failure
+    pub fn watch(&self, path: &str, recursive: bool) -> Result<u32, VfsError> {
+        let request = WatchRequest {
+            path: path.to_string(),
+            recursive,
+        };
+        let response: WatchResponse = self.call(vfs_msg::MSG_VFS_WATCH, &request)?;
+        response.result
+    }
+
+    /// Remove a watch.
+    pub fn unwatch(&self, id: u32) -> Result<(), VfsError> {
+        let request = UnwatchRequest { id };
+        let response: UnwatchResponse = self.call(vfs_msg::MSG_VFS_UNWATCH, &request)?;
+        response.result
+    }
+
+    /// Get a user's storage quota and current usage.
+    ///
+    /// # Returns
+    /// - `Ok(quota)` with `used_bytes` as counted by the VFS service
+    /// - `Err(VfsError)` on failure
+    pub fn quota_stat(&self, user_id: UserId) -> Result<StorageQuota, VfsError> {
+        let request = QuotaStatRequest { user_id };
+        let response: QuotaStatResponse = self.call(vfs_msg::MSG_VFS_QUOTA_STAT, &request)?;
+        response.result
+    }
+
     /// Delete a file.
     ///
     /// # Arguments
@@ -301,16 +466,158 @@ impl VfsClient {
     pub fn unlink(&self, path: &str) -> Result<(), VfsError> {
         let request = UnlinkRequest {
             path: path.to_string(),
+            trash: false,
+        };
+        let response: UnlinkResponse = self.call(vfs_msg::MSG_VFS_UNLINK, &request)?;
+        response.result
+    }
+
+    /// Move a file or directory to the user's trash, from where it can be
+    /// restored until it is purged.
+    pub fn trash(&self, path: &str) -> Result<(), VfsError> {
+        let request = UnlinkRequest {
+            path: path.to_string(),
+            trash: true,
         };
         let response: UnlinkResponse = self.call(vfs_msg::MSG_VFS_UNLINK, &request)?;
         response.result
     }
 
+    /// List a user's trashed files, newest first.
+    pub fn list_trash(&self, user_id: UserId) -> Result<Vec<TrashEntry>, VfsError> {
+        let request = TrashListRequest { user_id };
+        let response: TrashListResponse = self.call(vfs_msg::MSG_VFS_TRASH_LIST, &request)?;
+        response.result
+    }
+
+    /// Move a trashed file back to where it was unlinked from, or to `to`.
+    ///
+    /// Returns the path the file was restored to.
+    pub fn restore_trash(
+        &self,
+        user_id: UserId,
+        id: &str,
+        to: Option<&str>,
+    ) -> Result<String, VfsError> {
+        let request = TrashRestoreRequest {
+            user_id,
+            id: id.to_string(),
+            to: to.map(String::from),
+        };
+        let response: TrashRestoreResponse =
+            self.call(vfs_msg::MSG_VFS_TRASH_RESTORE, &request)?;
+        response.result
+    }
+
+    /// Delete a trashed entry for good, or every one if `id` is `None`.
+    ///
+    /// Returns the number of entries deleted.
+    pub fn purge_trash(&self, user_id: UserId, id: Option<&str>) -> Result<u32, VfsError> {
+        let request = TrashPurgeRequest {
+            user_id,
+            id: id.map(String::from),
+        };
+        let response: TrashPurgeResponse = self.call(vfs_msg::MSG_VFS_TRASH_PURGE, &request)?;
+        response.result
+    }
+
+    /// Snapshot the directory tree at `path`, a home directory or a
+    /// directory under one.
+    ///
+    /// Returns the new snapshot.
+    pub fn create_snapshot(
+        &self,
+        path: &str,
+        label: Option<&str>,
+    ) -> Result<SnapshotInfo, VfsError> {
+        let request = SnapshotCreateRequest {
+            path: path.into(),
+            label: label.map(String::from),
+        };
+        let response: SnapshotCreateResponse =
+            self.call(vfs_msg::MSG_VFS_SNAPSHOT_CREATE, &request)?;
+        response.result
+    }
+
+    /// List a user's snapshots, oldest first.
+    pub fn list_snapshots(&self, user_id: UserId) -> Result<Vec<SnapshotInfo>, VfsError> {
+        let request = SnapshotListRequest { user_id };
+        let response: SnapshotListResponse = self.call(vfs_msg::MSG_VFS_SNAPSHOT_LIST, &request)?;
+        response.result
+    }
+
+    /// Roll the tree a snapshot was taken of back to it.
+    ///
+    /// Entries created since are removed and changed ones put back; the
+    /// snapshot is kept.
+    pub fn restore_snapshot(&self, user_id: UserId, id: &str) -> Result<(), VfsError> {
+        let request = SnapshotRestoreRequest {
+            user_id,
+            id: id.into(),
+        };
+        let response: SnapshotRestoreResponse =
+            self.call(vfs_msg::MSG_VFS_SNAPSHOT_RESTORE, &request)?;
+        response.result
+    }
+
+    /// Delete a snapshot, freeing content only it still holds.
+    pub fn delete_snapshot(&self, user_id: UserId, id: &str) -> Result<(), VfsError> {
+        let request = SnapshotDeleteRequest {
+            user_id,
+            id: id.into(),
+        };
+        let response: SnapshotDeleteResponse =
+            self.call(vfs_msg::MSG_VFS_SNAPSHOT_DELETE, &request)?;
+        response.result
+    }
+
     /// Alias for unlink - delete a file.
     pub fn delete(&self, path: &str) -> Result<(), VfsError> {
         self.unlink(path)
     }
 
+    /// Rename or move a file or directory.
+    ///
+    /// Content is not sent back to the client; a directory moves with
+    /// everything in it.
+    ///
+    /// # Arguments
+    /// - `from`: Current path
+    /// - `to`: New path; its parent must exist and `to` must not
+    ///
+    /// # Returns
+    /// - `Ok(())` on success
+    /// - `Err(VfsError)` on failure
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), VfsError> {
+        let request = RenameRequest {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        let response: RenameResponse = self.call(vfs_msg::MSG_VFS_RENAME, &request)?;
+        response.result
+    }
+
+    /// Make `link_path` a second name for the file at `path`.
+    ///
+    /// Both names share one stored copy of the content until either is
+    /// rewritten; removing one leaves the other intact.
+    ///
+    /// # Arguments
+    /// - `path`: Existing file, owned by the user `link_path` belongs to
+    /// - `link_path`: New path; its parent must exist and it must not
+    ///
+    /// # Returns
+    /// - `Ok(())` on success
+    /// - `Err(VfsError)` on failure
+    pub fn link(&self, path: &str, link_path: &str) -> Result<(), VfsError> {
+        let request = LinkRequest {
+            path: path.to_string(),
+            link_path: link_path.to_string(),
+        };
+        let response: LinkResponse = self.call(vfs_msg::MSG_VFS_LINK, &request)?;
+        response.result
+    }
+
     /// Get file/directory metadata.
     ///
     /// # Arguments
@@ -344,6 +651,51 @@ impl VfsClient {
         response.result
     }
 
+    /// Get the SHA-256 of a file's content.
+    ///
+    /// The VFS hashes the file as it streams it from storage, so this works
+    /// for files too large to read in one request. Fails with a corrupt
+    /// storage error (see `VfsError::is_corrupt`) if the content no longer
+    /// matches the hash its inode records.
+    pub fn hash(&self, path: &str) -> Result<FileHash, VfsError> {
+        let request = HashRequest {
+            path: path.to_string(),
+        };
+        let response: HashResponse = self.call(vfs_msg::MSG_VFS_HASH, &request)?;
+        response.result
+    }
+
+    /// Set an extended attribute on a file or directory, or remove it if
+    /// `value` is `None`.
+    pub fn set_xattr(&self, path: &str, name: &str, value: Option<&str>) -> Result<(), VfsError> {
+        let request = SetXattrRequest {
+            path: path.to_string(),
+            name: name.to_string(),
+            value: value.map(String::from),
+        };
+        let response: SetXattrResponse = self.call(vfs_msg::MSG_VFS_SETXATTR, &request)?;
+        response.result
+    }
+
+    /// Get an extended attribute's value (`None` if it isn't set).
+    pub fn get_xattr(&self, path: &str, name: &str) -> Result<Option<String>, VfsError> {
+        let request = GetXattrRequest {
+            path: path.to_string(),
+            name: name.to_string(),
+        };
+        let response: GetXattrResponse = self.call(vfs_msg::MSG_VFS_GETXATTR, &request)?;
+        response.result
+    }
+
+    /// List the names of a file's or directory's extended attributes.
+    pub fn list_xattrs(&self, path: &str) -> Result<Vec<String>, VfsError> {
+        let request = ListXattrRequest {
+            path: path.to_string(),
+        };
+        let response: ListXattrResponse = self.call(vfs_msg::MSG_VFS_LISTXATTR, &request)?;
+        response.result
+    }
+
     /// Check if path is a directory.
     pub fn is_directory(&self, path: &str) -> Result<bool, VfsError> {
         match self.stat(path) {
@@ -363,13 +715,24 @@ impl VfsClient {
     }
 
     /// Internal: Send IPC request and receive response.
-    #[cfg(target_arch = "wasm32")]
     fn call<Req: serde::Serialize, Resp: serde::de::DeserializeOwned>(
         &self,
         tag: u32,
         request: &Req,
     ) -> Result<Resp, VfsError> {
-        use zos_process::{debug, receive_blocking, send};
+        self.call_with_caps(tag, request, &[])
+    }
+
+    /// Like `call`, transferring the capabilities in `cap_slots` with the
+    /// request.
+    #[cfg(target_arch = "wasm32")]
+    fn call_with_caps<Req: serde::Serialize, Resp: serde::de::DeserializeOwned>(
+        &self,
+        tag: u32,
+        request: &Req,
+        cap_slots: &[u32],
+    ) -> Result<Resp, VfsError> {
+        use zos_process::{debug, receive_blocking, send, send_with_caps};
 
         // VFS protocol: response tag = request tag + 1
         let expected_response_tag = tag + 1;
@@ -379,8 +742,12 @@ impl VfsClient {
             .map_err(|e| VfsError::StorageError(alloc::format!("Serialize error: {}", e)))?;
 
         // Send request to VFS service via our capability slot
-        send(self.vfs_endpoint, tag, &data)
-            .map_err(|e| VfsError::StorageError(alloc::format!("Send error: {}", e)))?;
+        let sent = if cap_slots.is_empty() {
+            send(self.vfs_endpoint, tag, &data)
+        } else {
+            send_with_caps(self.vfs_endpoint, tag, &data, cap_slots)
+        };
+        sent.map_err(|e| VfsError::StorageError(alloc::format!("Send error: {}", e)))?;
 
         // Wait for response on dedicated VFS response endpoint (slot 4)
         // This uses a separate endpoint from the general input slot (slot 1) to prevent
@@ -412,10 +779,11 @@ impl VfsClient {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    fn call<Req: serde::Serialize, Resp: serde::de::DeserializeOwned>(
+    fn call_with_caps<Req: serde::Serialize, Resp: serde::de::DeserializeOwned>(
         &self,
         _tag: u32,
         _request: &Req,
+        _cap_slots: &[u32],
     ) -> Result<Resp, VfsError> {
         Err(VfsError::StorageError(String::from(
             "VFS IPC not available outside WASM",
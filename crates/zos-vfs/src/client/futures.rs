@@ -0,0 +1,457 @@
+//! Async/await VFS client for event-driven services
+//!
+//! [`async_ops`](super::async_ops) leaves each service to remember what it
+//! asked the VFS for and pick up where it left off when the response comes
+//! back, which is a `PendingOp` enum, a request map and a response handler
+//! per operation. This module keeps that state in futures instead:
+//!
+//! ```ignore
+//! use zos_vfs::client::futures::{Vfs, VfsExecutor};
+//!
+//! enum Done {
+//!     Saved(Result<(), String>),
+//! }
+//!
+//! struct MyService {
+//!     vfs: VfsExecutor<Done>,
+//! }
+//!
+//! impl MyService {
+//!     fn save(&mut self, data: Vec<u8>) {
+//!         let vfs: Vfs = self.vfs.handle();
+//!         self.vfs.spawn(async move {
+//!             if !vfs.exists("/home/1/notes").await.unwrap_or(false) {
+//!                 let _ = vfs.mkdir("/home/1/notes").await;
+//!             }
+//!             Done::Saved(vfs.write("/home/1/notes/today.txt", &data).await)
+//!         });
+//!     }
+//!
+//!     fn on_message(&mut self, msg: Message) {
+//!         if self.vfs.on_message(msg.tag, &msg.data) {
+//!             while let Some(done) = self.vfs.next_finished() {
+//!                 // Handle the result with full access to `self`
+//!             }
+//!             return;
+//!         }
+//!         // Handle other messages
+//!     }
+//! }
+//! ```
+//!
+//! # Executor
+//!
+//! Tasks are polled when spawned and again whenever a response they wait on
+//! arrives; nothing else wakes them, so tasks may only await VFS calls
+//! (directly or through `async` code that does). A finished task's output
+//! is queued until the service takes it, so handling it needs no shared
+//! state between the task and the service.
+//!
+//! VFS responses carry no request IDs. They are matched to requests by
+//! response tag, oldest first, which only works if every VFS request of the
+//! process goes through one executor.
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+use super::async_ops;
+use crate::core::{DirEntry, Inode, VfsError};
+use crate::ipc::{vfs_msg, FileHash};
+
+/// State shared between the executor and its [`Vfs`] handles.
+#[derive(Default)]
+struct Shared {
+    /// Requests sent and not answered: (response tag, call ID), oldest first
+    in_flight: VecDeque<(u32, u32)>,
+    /// Responses not yet taken by their call
+    responses: BTreeMap<u32, Vec<u8>>,
+    /// Task waiting on each call
+    waiters: BTreeMap<u32, u32>,
+    /// Calls whose future was dropped; their responses are discarded
+    abandoned: BTreeSet<u32>,
+    /// Task being polled
+    current_task: u32,
+    next_call: u32,
+}
+
+/// Runs tasks that talk to the VFS and collects their outputs.
+pub struct VfsExecutor<T> {
+    shared: Rc<RefCell<Shared>>,
+    tasks: BTreeMap<u32, Pin<Box<dyn Future<Output = T>>>>,
+    finished: VecDeque<T>,
+    next_task: u32,
+}
+
+impl<T> Default for VfsExecutor<T> {
+    fn default() -> Self {
+        Self {
+            shared: Rc::default(),
+            tasks: BTreeMap::new(),
+            finished: VecDeque::new(),
+            next_task: 1,
+        }
+    }
+}
+
+impl<T: 'static> VfsExecutor<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A handle for making VFS calls from tasks of this executor.
+    pub fn handle(&self) -> Vfs {
+        Vfs {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Start a task; it runs until its first unanswered VFS call. Returns
+    /// the task's ID.
+    pub fn spawn(&mut self, task: impl Future<Output = T> + 'static) -> u32 {
+        let id = self.next_task;
+        self.next_task = self.next_task.wrapping_add(1).max(1);
+        self.tasks.insert(id, Box::pin(task));
+        self.poll_task(id);
+        id
+    }
+
+    /// Deliver a message if it answers a VFS call of this executor, and run
+    /// the task waiting on it. Returns false for any other message.
+    pub fn on_message(&mut self, tag: u32, data: &[u8]) -> bool {
+        let waiter = {
+            let mut shared = self.shared.borrow_mut();
+            let Some((_, call)) = shared
+                .in_flight
+                .iter()
+                .position(|(t, _)| *t == tag)
+                .and_then(|index| shared.in_flight.remove(index))
+            else {
+                return false;
+            };
+            if shared.abandoned.remove(&call) {
+                return true;
+            }
+            shared.responses.insert(call, data.to_vec());
+            shared.waiters.remove(&call)
+        };
+        if let Some(task) = waiter {
+            self.poll_task(task);
+        }
+        true
+    }
+
+    /// Take the output of the oldest finished task.
+    pub fn next_finished(&mut self) -> Option<T> {
+        self.finished.pop_front()
+    }
+
+    /// Tasks still running.
+    pub fn running(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// VFS calls sent and not yet answered.
+    pub fn in_flight(&self) -> usize {
+        self.shared.borrow().in_flight.len()
+    }
+
+    fn poll_task(&mut self, id: u32) {
+        let Some(task) = self.tasks.get_mut(&id) else {
+            return;
+        };
+        self.shared.borrow_mut().current_task = id;
+        let mut cx = Context::from_waker(Waker::noop());
+        if let Poll::Ready(output) = task.as_mut().poll(&mut cx) {
+            self.tasks.remove(&id);
+            self.finished.push_back(output);
+        }
+    }
+}
+
+/// Makes VFS calls from tasks of a [`VfsExecutor`]. Cheap to clone.
+#[derive(Clone)]
+pub struct Vfs {
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl Vfs {
+    /// Send a request with `send` and wait for the message tagged
+    /// `response_tag` that answers it.
+    pub fn call(&self, response_tag: u32, send: impl FnOnce() -> Result<(), VfsError>) -> VfsCall {
+        let call = match send() {
+            Ok(()) => {
+                let mut shared = self.shared.borrow_mut();
+                let call = shared.next_call;
+                shared.next_call = shared.next_call.wrapping_add(1);
+                shared.in_flight.push_back((response_tag, call));
+                Ok(call)
+            }
+            Err(e) => Err(format!("{:?}", e)),
+        };
+        VfsCall {
+            shared: self.shared.clone(),
+            call: Some(call),
+        }
+    }
+
+    /// Read a whole file.
+    pub async fn read(&self, path: &str) -> Result<Vec<u8>, String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_READ_RESPONSE, || {
+                async_ops::send_read_request(path)
+            })
+            .await?;
+        async_ops::parse_read_response(&data)
+    }
+
+    /// Write a whole file, creating or replacing it.
+    pub async fn write(&self, path: &str, content: &[u8]) -> Result<(), String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_WRITE_RESPONSE, || {
+                async_ops::send_write_request(path, content)
+            })
+            .await?;
+        async_ops::parse_write_response(&data)
+    }
+
+    /// Check whether a path exists.
+    pub async fn exists(&self, path: &str) -> Result<bool, String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_EXISTS_RESPONSE, || {
+                async_ops::send_exists_request(path)
+            })
+            .await?;
+        async_ops::parse_exists_response(&data)
+    }
+
+    /// Create a directory and any missing parents.
+    pub async fn mkdir(&self, path: &str) -> Result<(), String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_MKDIR_RESPONSE, || {
+                async_ops::send_mkdir_request(path, true)
+            })
+            .await?;
+        async_ops::parse_mkdir_response(&data)
+    }
+
+    /// Delete a file.
+    pub async fn unlink(&self, path: &str) -> Result<(), String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_UNLINK_RESPONSE, || {
+                async_ops::send_unlink_request(path)
+            })
+            .await?;
+        async_ops::parse_unlink_response(&data)
+    }
+
+    /// Rename or move a file or directory.
+    pub async fn rename(&self, from: &str, to: &str) -> Result<(), String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_RENAME_RESPONSE, || {
+                async_ops::send_rename_request(from, to)
+            })
+            .await?;
+        async_ops::parse_rename_response(&data)
+    }
+
+    /// List a directory.
+    pub async fn readdir(&self, path: &str) -> Result<Vec<DirEntry>, String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_READDIR_RESPONSE, || {
+                async_ops::send_readdir_request(path)
+            })
+            .await?;
+        async_ops::parse_readdir_response(&data)
+    }
+
+    /// Get the inode at a path.
+    pub async fn stat(&self, path: &str) -> Result<Inode, String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_STAT_RESPONSE, || {
+                async_ops::send_stat_request(path)
+            })
+            .await?;
+        async_ops::parse_stat_response(&data)
+    }
+
+    /// SHA-256 of a file, computed by the VFS without sending the content.
+    pub async fn hash(&self, path: &str) -> Result<FileHash, String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_HASH_RESPONSE, || {
+                async_ops::send_hash_request(path)
+            })
+            .await?;
+        async_ops::parse_hash_response(&data)
+    }
+
+    /// Open a file for partial I/O; `create` makes an empty file if none
+    /// exists (requires `write`).
+    pub async fn open(&self, path: &str, write: bool, create: bool) -> Result<u32, String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_OPEN_RESPONSE, || {
+                async_ops::send_open_request(path, write, create)
+            })
+            .await?;
+        async_ops::parse_open_response(&data)
+    }
+
+    /// Read up to `length` bytes at `offset`; fewer at end of file or past
+    /// the VFS's per-reply limit.
+    pub async fn read_at(&self, handle: u32, offset: u64, length: u64) -> Result<Vec<u8>, String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_READ_AT_RESPONSE, || {
+                async_ops::send_read_at_request(handle, offset, length)
+            })
+            .await?;
+        async_ops::parse_read_at_response(&data)
+    }
+
+    /// Write `bytes` at `offset`, returning the file's new size.
+    pub async fn write_at(&self, handle: u32, offset: u64, bytes: &[u8]) -> Result<u64, String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_WRITE_AT_RESPONSE, || {
+                async_ops::send_write_at_request(handle, offset, bytes)
+            })
+            .await?;
+        async_ops::parse_write_at_response(&data)
+    }
+
+    /// Close a handle.
+    pub async fn close(&self, handle: u32) -> Result<(), String> {
+        let data = self
+            .call(vfs_msg::MSG_VFS_CLOSE_RESPONSE, || {
+                async_ops::send_close_request(handle)
+            })
+            .await?;
+        async_ops::parse_close_response(&data)
+    }
+}
+
+/// A VFS call in flight; resolves to the raw response payload, or to the
+/// error that kept the request from being sent.
+pub struct VfsCall {
+    shared: Rc<RefCell<Shared>>,
+    /// Call ID, or why sending failed; None once resolved
+    call: Option<Result<u32, String>>,
+}
+
+impl Future for VfsCall {
+    type Output = Result<Vec<u8>, String>;
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let call = match self.call.take() {
+            Some(Ok(call)) => call,
+            Some(Err(e)) => return Poll::Ready(Err(e)),
+            None => return Poll::Ready(Err(String::from("VFS call polled after completion"))),
+        };
+        let mut shared = self.shared.borrow_mut();
+        if let Some(data) = shared.responses.remove(&call) {
+            return Poll::Ready(Ok(data));
+        }
+        let task = shared.current_task;
+        shared.waiters.insert(call, task);
+        drop(shared);
+        self.call = Some(Ok(call));
+        Poll::Pending
+    }
+}
+
+impl Drop for VfsCall {
+    fn drop(&mut self) {
+        let Some(Ok(call)) = self.call else {
+            return;
+        };
+        let mut shared = self.shared.borrow_mut();
+        shared.waiters.remove(&call);
+        if shared.responses.remove(&call).is_none() {
+            shared.abandoned.insert(call);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const READ: u32 = vfs_msg::MSG_VFS_READ_RESPONSE;
+    const WRITE: u32 = vfs_msg::MSG_VFS_WRITE_RESPONSE;
+    const EXISTS: u32 = vfs_msg::MSG_VFS_EXISTS_RESPONSE;
+
+    fn read_ok(content: &[u8]) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({ "result": { "Ok": content } })).unwrap()
+    }
+
+    #[test]
+    fn test_task_runs_between_responses() {
+        let mut executor = VfsExecutor::new();
+        let vfs = executor.handle();
+        executor.spawn(async move {
+            let content = vfs.read("/a").await?;
+            vfs.write("/b", &content).await?;
+            Ok::<usize, String>(content.len())
+        });
+        assert_eq!(executor.running(), 1);
+        assert_eq!(executor.in_flight(), 1);
+
+        // Not a response it is waiting for
+        assert!(!executor.on_message(WRITE, b"{}"));
+        assert!(!executor.on_message(0x1000, b"{}"));
+
+        assert!(executor.on_message(READ, &read_ok(b"hi")));
+        assert!(executor.next_finished().is_none());
+        assert!(executor.on_message(WRITE, br#"{"result":{"Ok":null}}"#));
+        assert_eq!(executor.next_finished(), Some(Ok(2)));
+        assert_eq!(executor.running(), 0);
+        assert_eq!(executor.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_responses_match_oldest_call() {
+        let mut executor = VfsExecutor::new();
+        for path in ["/first", "/second"] {
+            let vfs = executor.handle();
+            executor.spawn(async move { (path, vfs.exists(path).await) });
+        }
+        assert!(executor.on_message(EXISTS, br#"{"result":{"Ok":true}}"#));
+        assert!(executor.on_message(EXISTS, br#"{"result":{"Ok":false}}"#));
+        assert_eq!(executor.next_finished(), Some(("/first", Ok(true))));
+        assert_eq!(executor.next_finished(), Some(("/second", Ok(false))));
+    }
+
+    #[test]
+    fn test_errors_and_abandoned_calls() {
+        let mut executor = VfsExecutor::new();
+        let vfs = executor.handle();
+
+        // A request that could not be sent fails without waiting
+        executor.spawn(async move { vfs.call(READ, || Err(VfsError::NotFound)).await });
+        assert!(executor.next_finished().unwrap().is_err());
+        assert_eq!(executor.in_flight(), 0);
+
+        // A call dropped before its response swallows the response
+        let vfs = executor.handle();
+        let call = vfs.call(READ, || Ok(()));
+        drop(call);
+        let vfs = executor.handle();
+        executor.spawn(async move { vfs.read("/a").await });
+        assert!(executor.on_message(READ, &read_ok(b"old")));
+        assert!(executor.next_finished().is_none());
+        assert!(executor.on_message(READ, &read_ok(b"new")));
+        assert_eq!(executor.next_finished(), Some(Ok(vec![b'n', b'e', b'w'])));
+
+        // VFS errors come back as strings
+        let vfs = executor.handle();
+        executor.spawn(async move { vfs.read("/missing").await });
+        assert!(executor.on_message(READ, br#"{"result":{"Err":"NotFound"}}"#));
+        assert!(executor.next_finished().unwrap().is_err());
+    }
+}
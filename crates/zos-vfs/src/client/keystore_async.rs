@@ -98,6 +98,8 @@ pub enum KeystoreError {
     StorageError(String),
     /// Too many pending operations
     ResourceExhausted,
+    /// Request was cancelled by the client before it completed
+    Cancelled,
 }
 
 /// Read key response.
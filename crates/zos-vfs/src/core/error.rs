@@ -114,6 +114,17 @@ pub enum VfsError {
     /// Quota exceeded
     QuotaExceeded,
 
+    /// Write refused because it would take the owner past their storage
+    /// quota
+    UserQuotaExceeded {
+        /// Bytes the owner already uses
+        used: u64,
+        /// The owner's quota in bytes
+        limit: u64,
+        /// Bytes the write would add
+        requested: u64,
+    },
+
     /// File too large
     FileTooLarge,
 
@@ -128,6 +139,14 @@ pub enum VfsError {
 
     /// Operation not supported
     NotSupported(String),
+
+    /// Service is temporarily not accepting requests (e.g. draining before
+    /// a restart); the request was not started and can be sent again
+    Retry(String),
+
+    /// Request was cancelled by the client before it completed; any
+    /// writes it made may or may not have landed
+    Cancelled,
 }
 
 impl VfsError {
@@ -162,6 +181,21 @@ impl VfsError {
         Self::InvalidPath(msg.into())
     }
 
+    /// Create a retryable error with the reason the request was refused.
+    pub fn retry(msg: impl Into<String>) -> Self {
+        Self::Retry(msg.into())
+    }
+
+    /// Check if the request can simply be sent again.
+    pub fn is_retry(&self) -> bool {
+        matches!(self, VfsError::Retry(_))
+    }
+
+    /// Check if the client cancelled the request.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, VfsError::Cancelled)
+    }
+
     /// Check if this is a "not found" error (including storage key not found).
     pub fn is_not_found(&self) -> bool {
         matches!(
@@ -170,6 +204,33 @@ impl VfsError {
         )
     }
 
+    /// Check if a storage operation timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(
+            self,
+            VfsError::Storage {
+                kind: StorageErrorKind::Timeout { .. },
+                ..
+            }
+        )
+    }
+
+    /// Check if a write was refused for the owner's storage quota.
+    pub fn is_quota_exceeded(&self) -> bool {
+        matches!(self, VfsError::UserQuotaExceeded { .. })
+    }
+
+    /// Check if content failed an integrity check.
+    pub fn is_corrupt(&self) -> bool {
+        matches!(
+            self,
+            VfsError::Storage {
+                kind: StorageErrorKind::ChunkCorrupt { .. },
+                ..
+            }
+        )
+    }
+
     /// Check if this is a permission error.
     pub fn is_permission_denied(&self) -> bool {
         matches!(
@@ -237,4 +298,24 @@ mod tests {
             .is_permission_denied());
         assert!(!VfsError::NotFound.is_permission_denied());
     }
+
+    #[test]
+    fn test_is_retry() {
+        assert!(VfsError::retry("draining").is_retry());
+        assert!(!VfsError::storage_error(StorageErrorKind::Unavailable).is_retry());
+    }
+
+    #[test]
+    fn test_is_cancelled() {
+        assert!(VfsError::Cancelled.is_cancelled());
+        assert!(!VfsError::Cancelled.is_retry());
+        assert!(!VfsError::retry("draining").is_cancelled());
+    }
+
+    #[test]
+    fn test_is_timeout() {
+        assert!(VfsError::storage_error(StorageErrorKind::timeout("read")).is_timeout());
+        assert!(!VfsError::storage_error(StorageErrorKind::Unavailable).is_timeout());
+        assert!(!VfsError::Cancelled.is_timeout());
+    }
 }
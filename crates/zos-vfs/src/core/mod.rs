@@ -6,4 +6,7 @@ mod types;
 
 pub use error::{StorageErrorKind, VfsError};
 pub use path::{extract_user_id, filename, is_under, join_path, normalize_path, parent_path, validate_path};
-pub use types::{DirEntry, FilePermissions, Inode, InodeType, UserId};
+pub use types::{
+    validate_xattr_name, DirEntry, FilePermissions, Inode, InodeType, UserId, Xattrs,
+    MAX_XATTRS_SIZE, MAX_XATTR_NAME_LEN, MAX_XATTR_VALUE_LEN,
+};
@@ -102,26 +102,36 @@ pub fn join_path(base: &str, name: &str) -> String {
 }
 
 /// Check if a path is under a given base path.
+///
+/// Both paths are normalized first, so `..` components and trailing slashes
+/// cannot make a path appear under a base it escapes. Paths that fail to
+/// normalize are never under anything.
 pub fn is_under(path: &str, base: &str) -> bool {
+    let (Ok(path), Ok(base)) = (normalize_path(path), normalize_path(base)) else {
+        return false;
+    };
     if base == "/" {
         return true;
     }
 
-    path.starts_with(base) && (path.len() == base.len() || path.as_bytes()[base.len()] == b'/')
+    path.starts_with(&base) && (path.len() == base.len() || path.as_bytes()[base.len()] == b'/')
 }
 
 /// Extract the user ID from a home directory path.
 /// Returns None if the path is not under /home/{user_id}/
+///
+/// The path is normalized first and the user ID component must be decimal
+/// digits only, matching the `/home/{user_id}` layout used by
+/// `VfsService::get_home_dir`.
 pub fn extract_user_id(path: &str) -> Option<u128> {
-    if !path.starts_with("/home/") {
-        return None;
-    }
-
-    let rest = &path[6..]; // Skip "/home/"
+    let path = normalize_path(path).ok()?;
+    let rest = path.strip_prefix("/home/")?;
     let user_id_str = rest.split('/').next()?;
 
-    // Try to parse as hex UUID
-    u128::from_str_radix(user_id_str, 16).ok()
+    if user_id_str.is_empty() || !user_id_str.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    user_id_str.parse::<u128>().ok()
 }
 
 #[cfg(test)]
@@ -179,6 +189,10 @@ mod tests {
         assert!(is_under("/anything", "/"));
         assert!(!is_under("/home", "/home/user"));
         assert!(!is_under("/homeuser", "/home")); // Not a proper prefix
+        assert!(is_under("/home/user", "/home/user/")); // Trailing slash on base
+        assert!(!is_under("/home/user/../../etc", "/home/user")); // Escapes via ..
+        assert!(!is_under("/home/user/..", "/home/user"));
+        assert!(!is_under("/home/user/\0", "/home/user"));
     }
 
     #[test]
@@ -189,5 +203,213 @@ mod tests {
         );
         assert_eq!(extract_user_id("/system/config"), None);
         assert_eq!(extract_user_id("/tmp"), None);
+        assert_eq!(extract_user_id("/home/42"), Some(42));
+        assert_eq!(extract_user_id("/home/1/../2/docs"), Some(2));
+        assert_eq!(extract_user_id("/home/+1"), None);
+        assert_eq!(extract_user_id("/home/ff"), None);
+        assert_eq!(extract_user_id("/home/"), None);
+        assert_eq!(extract_user_id("/home"), None);
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    extern crate std;
+
+    use super::*;
+    use alloc::vec;
+    use proptest::prelude::*;
+
+    /// Reference normalization, written independently of `normalize_path`:
+    /// scans characters and keeps an explicit stack of component boundaries.
+    /// Returns `None` for paths `normalize_path` must reject.
+    fn oracle_normalize(path: &str) -> Option<String> {
+        if !path.starts_with('/') || path.contains('\0') {
+            return None;
+        }
+        let mut out = String::from("/");
+        let mut starts: Vec<usize> = Vec::new();
+        let mut component = String::new();
+        for c in path.chars().chain(core::iter::once('/')) {
+            if c != '/' {
+                component.push(c);
+                continue;
+            }
+            match component.as_str() {
+                "" | "." => {}
+                ".." => {
+                    let start = starts.pop()?;
+                    out.truncate(start);
+                }
+                name => {
+                    starts.push(out.len());
+                    if out.len() > 1 {
+                        out.push('/');
+                    }
+                    out.push_str(name);
+                }
+            }
+            component.clear();
+        }
+        Some(out)
+    }
+
+    /// Paths that have broken path handling elsewhere, with the expected
+    /// normalization (`None` = rejected).
+    const ADVERSARIAL: &[(&str, Option<&str>)] = &[
+        ("", None),
+        ("relative", None),
+        ("./a", None),
+        ("/", Some("/")),
+        ("//", Some("/")),
+        ("///a///b///", Some("/a/b")),
+        ("/.", Some("/")),
+        ("/./.", Some("/")),
+        ("/..", None),
+        ("/../a", None),
+        ("/a/../..", None),
+        ("/a/b/../../..", None),
+        ("/a/..", Some("/")),
+        ("/a/./b/./", Some("/a/b")),
+        ("/a/b/..", Some("/a")),
+        ("/a/b/../", Some("/a")),
+        ("/...", Some("/...")),
+        ("/..a/a..", Some("/..a/a..")),
+        ("/. /.. ", Some("/. /.. ")),
+        ("/a\0", None),
+        ("/\0/..", None),
+        ("/a/\\..", Some("/a/\\..")),
+        ("/home/\u{2215}..", Some("/home/\u{2215}..")),
+        ("/home/\u{FF0E}\u{FF0E}", Some("/home/\u{FF0E}\u{FF0E}")),
+        ("/\u{202E}/\u{200B}..", Some("/\u{202E}/\u{200B}..")),
+        ("/日本/../語", Some("/語")),
+        ("/é/./e\u{301}", Some("/é/e\u{301}")),
+    ];
+
+    #[test]
+    fn adversarial_corpus() {
+        for (input, expected) in ADVERSARIAL {
+            let actual = normalize_path(input).ok();
+            assert_eq!(actual.as_deref(), *expected, "normalize_path({:?})", input);
+            assert_eq!(
+                oracle_normalize(input).as_deref(),
+                *expected,
+                "oracle({:?})",
+                input
+            );
+        }
+    }
+
+    /// A single path component, biased towards the interesting ones.
+    fn component() -> impl Strategy<Value = String> {
+        prop_oneof![
+            3 => Just(String::new()),
+            3 => Just(String::from(".")),
+            4 => Just(String::from("..")),
+            1 => Just(String::from("...")),
+            1 => Just(String::from("\0")),
+            1 => Just(String::from("a\0b")),
+            1 => Just(String::from("\u{2215}")),
+            1 => Just(String::from("\u{FF0E}\u{FF0E}")),
+            1 => Just(String::from("\\")),
+            6 => "[a-c]{1,3}",
+            3 => "\\PC{1,4}".prop_map(|s| s.replace('/', "")),
+        ]
+    }
+
+    /// Absolute or relative path built from random components.
+    fn path() -> impl Strategy<Value = String> {
+        (
+            prop::bool::weighted(0.9),
+            prop::collection::vec(component(), 0..8),
+            prop::bool::ANY,
+        )
+            .prop_map(|(absolute, components, trailing)| {
+                let mut path = components.join("/");
+                if absolute {
+                    path.insert(0, '/');
+                }
+                if trailing {
+                    path.push('/');
+                }
+                path
+            })
+    }
+
+    /// A valid file name (no separators, NULs or dot components).
+    fn name() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9_\\-]{1,8}|\\PC{1,4}".prop_filter("plain name", |s| {
+            !s.contains('/') && !s.contains('\0') && s != "." && s != ".."
+        })
+    }
+
+    proptest! {
+        /// normalize_path agrees with the reference model on every input
+        #[test]
+        fn normalize_matches_oracle(path in path()) {
+            prop_assert_eq!(normalize_path(&path).ok(), oracle_normalize(&path));
+        }
+
+        /// Normalized paths are canonical: absolute, no empty, `.` or `..`
+        /// components, no trailing slash, and normalizing again is a no-op
+        #[test]
+        fn normalized_paths_are_canonical(path in path()) {
+            if let Ok(normalized) = normalize_path(&path) {
+                prop_assert!(normalized.starts_with('/'));
+                prop_assert!(validate_path(&normalized).is_ok());
+                if normalized != "/" {
+                    prop_assert!(!normalized.ends_with('/'));
+                    for c in normalized[1..].split('/') {
+                        prop_assert!(!c.is_empty() && c != "." && c != "..", "{:?}", normalized);
+                    }
+                }
+                prop_assert_eq!(normalize_path(&normalized).ok(), Some(normalized));
+            }
+        }
+
+        /// Anything built on top of a base either stays under it after
+        /// normalization or fails to normalize - `..` can never escape
+        #[test]
+        fn is_under_rejects_escapes(base in path(), suffix in path()) {
+            let joined = alloc::format!("{}/{}", base, suffix.trim_start_matches('/'));
+            if is_under(&joined, &base) {
+                let base = normalize_path(&base).unwrap();
+                let joined = normalize_path(&joined).unwrap();
+                prop_assert!(base == "/" || joined == base || joined.starts_with(&(base + "/")));
+            }
+        }
+
+        /// parent_path, filename and join_path round-trip on normalized paths
+        #[test]
+        fn parent_filename_join_round_trip(path in path(), child in name()) {
+            if let Ok(dir) = normalize_path(&path) {
+                let joined = join_path(&dir, &child);
+                prop_assert_eq!(normalize_path(&joined).ok(), Some(joined.clone()));
+                prop_assert_eq!(parent_path(&joined), dir.clone());
+                prop_assert_eq!(filename(&joined), child.as_str());
+                prop_assert!(is_under(&joined, &dir));
+            }
+        }
+
+        /// User IDs only come from the canonical home directory layout
+        #[test]
+        fn extract_user_id_uses_normalized_home(user_id in any::<u64>(), rest in path()) {
+            let home = alloc::format!("/home/{}", user_id);
+            let path = alloc::format!("{}/{}", home, rest.trim_start_matches('/'));
+            match normalize_path(&path) {
+                Ok(normalized) if is_under(&normalized, &home) => {
+                    prop_assert_eq!(extract_user_id(&path), Some(user_id as u128));
+                }
+                Ok(normalized) => {
+                    let expected = normalized
+                        .strip_prefix("/home/")
+                        .and_then(|r| r.split('/').next())
+                        .filter(|c| !c.is_empty() && c.bytes().all(|b| b.is_ascii_digit()))
+                        .and_then(|c| c.parse::<u128>().ok());
+                    prop_assert_eq!(extract_user_id(&path), expected);
+                }
+                Err(_) => prop_assert_eq!(extract_user_id(&path), None),
+            }
+        }
     }
 }
@@ -2,15 +2,21 @@
 //!
 //! Defines Inode, FilePermissions, and directory entry types.
 
+use alloc::collections::BTreeMap;
+use alloc::format;
 use alloc::string::String;
 use serde::{Deserialize, Serialize};
 
+use super::VfsError;
+use crate::schema::INODE_SCHEMA_VERSION;
+
 /// Serde helper for Option<u128> as hex string
 ///
 /// Large u128 values can cause issues with JSON serialization/deserialization
 /// when represented as decimal numbers. This helper serializes them as hex strings.
 mod option_u128_hex {
     use alloc::format;
+    use alloc::string::String;
     use serde::{self, Deserialize, Deserializer, Serializer};
 
     pub fn serialize<S>(value: &Option<u128>, serializer: S) -> Result<S::Ok, S::Error>
@@ -27,8 +33,9 @@ mod option_u128_hex {
     where
         D: Deserializer<'de>,
     {
-        let opt: Option<&str> = Option::deserialize(deserializer)?;
-        match opt {
+        // Owned, so records decoded from a `serde_json::Value` work too
+        let opt: Option<String> = Option::deserialize(deserializer)?;
+        match opt.as_deref() {
             Some(s) => {
                 // Handle both hex strings and legacy decimal numbers
                 if s.chars().all(|c| c.is_ascii_hexdigit()) && s.len() <= 32 {
@@ -50,9 +57,27 @@ mod option_u128_hex {
 /// A unique user identifier (UUID as 128-bit value).
 pub type UserId = u128;
 
+/// Extended attributes of an inode: small named values such as a MIME type,
+/// tags or an origin URL, kept in the inode record itself.
+pub type Xattrs = BTreeMap<String, String>;
+
+/// Maximum length of an extended attribute name in bytes
+pub const MAX_XATTR_NAME_LEN: usize = 255;
+
+/// Maximum length of an extended attribute value in bytes
+pub const MAX_XATTR_VALUE_LEN: usize = 4096;
+
+/// Maximum total size of an inode's extended attributes (names and values)
+pub const MAX_XATTRS_SIZE: usize = 16 * 1024;
+
 /// Virtual filesystem inode.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Inode {
+    /// On-disk schema version (0 for records written before versioning).
+    /// See [`crate::schema`].
+    #[serde(default)]
+    pub schema_version: u32,
+
     /// Canonical path (primary key)
     pub path: String,
 
@@ -90,6 +115,10 @@ pub struct Inode {
 
     /// SHA-256 hash of content (files only)
     pub content_hash: Option<[u8; 32]>,
+
+    /// Extended attributes (see [`Xattrs`])
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub xattrs: Xattrs,
 }
 
 impl Inode {
@@ -102,6 +131,7 @@ impl Inode {
         now: u64,
     ) -> Self {
         Self {
+            schema_version: INODE_SCHEMA_VERSION,
             path,
             parent_path,
             name,
@@ -114,6 +144,7 @@ impl Inode {
             size: 0,
             encrypted: false,
             content_hash: None,
+            xattrs: Xattrs::new(),
         }
     }
 
@@ -128,6 +159,7 @@ impl Inode {
         now: u64,
     ) -> Self {
         Self {
+            schema_version: INODE_SCHEMA_VERSION,
             path,
             parent_path,
             name,
@@ -140,6 +172,7 @@ impl Inode {
             size,
             encrypted: false,
             content_hash,
+            xattrs: Xattrs::new(),
         }
     }
 
@@ -157,6 +190,52 @@ impl Inode {
     pub fn is_symlink(&self) -> bool {
         matches!(self.inode_type, InodeType::SymLink { .. })
     }
+
+    /// Set an extended attribute, or remove it if `value` is `None`.
+    ///
+    /// Names must be 1 to `MAX_XATTR_NAME_LEN` bytes without control
+    /// characters; the attributes together may not exceed `MAX_XATTRS_SIZE`.
+    pub fn set_xattr(&mut self, name: &str, value: Option<String>) -> Result<(), VfsError> {
+        validate_xattr_name(name)?;
+        let Some(value) = value else {
+            self.xattrs.remove(name);
+            return Ok(());
+        };
+        if value.len() > MAX_XATTR_VALUE_LEN {
+            return Err(VfsError::InvalidRequest(format!(
+                "Attribute value is {} bytes, the limit is {}",
+                value.len(),
+                MAX_XATTR_VALUE_LEN
+            )));
+        }
+        let others: usize = self
+            .xattrs
+            .iter()
+            .filter(|(n, _)| n.as_str() != name)
+            .map(|(n, v)| n.len() + v.len())
+            .sum();
+        if others + name.len() + value.len() > MAX_XATTRS_SIZE {
+            return Err(VfsError::QuotaExceeded);
+        }
+        self.xattrs.insert(String::from(name), value);
+        Ok(())
+    }
+}
+
+/// Check an extended attribute name.
+pub fn validate_xattr_name(name: &str) -> Result<(), VfsError> {
+    if name.is_empty() || name.len() > MAX_XATTR_NAME_LEN {
+        return Err(VfsError::InvalidRequest(format!(
+            "Attribute name must be 1 to {} bytes",
+            MAX_XATTR_NAME_LEN
+        )));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(VfsError::InvalidRequest(String::from(
+            "Attribute name contains control characters",
+        )));
+    }
+    Ok(())
 }
 
 /// Type of filesystem entry.
@@ -394,4 +473,58 @@ mod tests {
         assert_eq!(entry.size, 500);
         assert!(!entry.is_directory);
     }
+
+    #[test]
+    fn test_xattrs() {
+        let mut inode = Inode::new_file(
+            String::from("/home/user/photo.png"),
+            String::from("/home/user"),
+            String::from("photo.png"),
+            Some(1),
+            0,
+            None,
+            0,
+        );
+        // No attributes: the record doesn't mention them
+        let json = serde_json::to_string(&inode).unwrap();
+        assert!(!json.contains("xattrs"));
+
+        inode
+            .set_xattr("mime_type", Some(String::from("image/png")))
+            .unwrap();
+        let again: Inode = serde_json::from_slice(&serde_json::to_vec(&inode).unwrap()).unwrap();
+        assert_eq!(again.xattrs.get("mime_type").unwrap(), "image/png");
+
+        inode.set_xattr("mime_type", None).unwrap();
+        assert!(inode.xattrs.is_empty());
+        // Removing a missing attribute is not an error
+        inode.set_xattr("mime_type", None).unwrap();
+
+        assert!(inode.set_xattr("", Some(String::new())).is_err());
+        assert!(inode.set_xattr("a\nb", Some(String::new())).is_err());
+        let long = "x".repeat(MAX_XATTR_VALUE_LEN + 1);
+        assert!(inode.set_xattr("long", Some(long)).is_err());
+    }
+
+    #[test]
+    fn test_xattrs_total_size() {
+        let mut inode = Inode::new_directory(
+            String::from("/home/user"),
+            String::from("/home"),
+            String::from("user"),
+            Some(1),
+            0,
+        );
+        let value = "v".repeat(MAX_XATTR_VALUE_LEN);
+        let fits = MAX_XATTRS_SIZE / (MAX_XATTR_VALUE_LEN + 2);
+        for i in 0..fits {
+            inode.set_xattr(&format!("a{}", i), Some(value.clone())).unwrap();
+        }
+        assert!(matches!(
+            inode.set_xattr("more", Some(value.clone())),
+            Err(VfsError::QuotaExceeded)
+        ));
+        // Replacing an attribute only counts its new value
+        inode.set_xattr("a0", Some(value)).unwrap();
+    }
 }
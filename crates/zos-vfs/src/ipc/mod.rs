@@ -14,8 +14,18 @@ pub use types::*;
 /// "Single Source of Truth for All Constants".
 pub mod vfs_msg {
     // Re-export all VFS constants from zos-ipc
+    pub use zos_ipc::vfs_batch::*;
+    pub use zos_ipc::vfs_caller::*;
     pub use zos_ipc::vfs_dir::*;
     pub use zos_ipc::vfs_file::*;
+    pub use zos_ipc::vfs_handle::*;
+    pub use zos_ipc::vfs_hash::*;
     pub use zos_ipc::vfs_meta::*;
+    pub use zos_ipc::vfs_mount::*;
     pub use zos_ipc::vfs_quota::*;
+    pub use zos_ipc::vfs_signed::*;
+    pub use zos_ipc::vfs_watch::*;
+    pub use zos_ipc::vfs_xattr::*;
+    pub use zos_ipc::vfs_trash::*;
+    pub use zos_ipc::vfs_snapshot::*;
 }
@@ -5,8 +5,11 @@ use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
 use crate::core::{DirEntry, FilePermissions, Inode, UserId, VfsError};
+use crate::mount::{MountBackend, MountPoint};
 use crate::storage::{StorageQuota, StorageUsage};
 
+use super::vfs_msg;
+
 // ============================================================================
 // Directory Request/Response Types
 // ============================================================================
@@ -41,6 +44,29 @@ pub struct RmdirRequest {
 pub struct RmdirResponse {
     /// Result of operation
     pub result: Result<(), VfsError>,
+    /// Entries a recursive remove could not delete. The directories above
+    /// them are kept too, and `result` is `DirectoryNotEmpty`.
+    #[serde(default)]
+    pub failed: Vec<RmdirFailure>,
+}
+
+impl RmdirResponse {
+    /// A response with no per-entry failures.
+    pub fn new(result: Result<(), VfsError>) -> Self {
+        Self {
+            result,
+            failed: Vec::new(),
+        }
+    }
+}
+
+/// An entry a recursive remove left in place.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RmdirFailure {
+    /// Path of the entry
+    pub path: String,
+    /// Why it could not be removed
+    pub error: VfsError,
 }
 
 /// Read directory request.
@@ -102,6 +128,9 @@ pub struct ReadFileResponse {
 pub struct UnlinkRequest {
     /// File path to delete
     pub path: String,
+    /// Move the file or directory to the user's trash instead of deleting it
+    #[serde(default)]
+    pub trash: bool,
 }
 
 /// Delete file response.
@@ -143,6 +172,266 @@ pub struct CopyResponse {
     pub result: Result<(), VfsError>,
 }
 
+/// Hard link request: make `link_path` a second name for the file at `path`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinkRequest {
+    /// Existing file
+    pub path: String,
+    /// New path, which must not exist yet
+    pub link_path: String,
+}
+
+/// Hard link response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinkResponse {
+    /// Result of operation
+    pub result: Result<(), VfsError>,
+}
+
+// ============================================================================
+// File Handle Request/Response Types
+// ============================================================================
+
+/// Open file request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpenRequest {
+    /// File path
+    pub path: String,
+    /// Open for writing as well as reading
+    pub write: bool,
+    /// Create an empty file if none exists (requires `write`)
+    pub create: bool,
+}
+
+/// Open file response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpenResponse {
+    /// Result containing the handle or error
+    pub result: Result<u32, VfsError>,
+}
+
+/// Read at offset request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReadAtRequest {
+    /// Handle from `OpenResponse`
+    pub handle: u32,
+    /// Byte offset to read from
+    pub offset: u64,
+    /// Maximum number of bytes to read
+    pub length: u64,
+    /// Place the bytes at the start of the handle's shared memory region
+    /// instead of in the response, up to the region's size
+    #[serde(default)]
+    pub shm: bool,
+}
+
+/// Read at offset response.
+///
+/// Fewer bytes than requested means end of file (or the per-reply limit);
+/// none means the offset is at or past the end.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReadAtResponse {
+    /// Result containing the bytes read or error; empty for a read into
+    /// shared memory
+    pub result: Result<Vec<u8>, VfsError>,
+    /// For a read into shared memory, the number of bytes placed there
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shm_len: Option<u64>,
+}
+
+/// Write at offset request.
+///
+/// Writing past the end of the file fills the gap with zeros.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WriteAtRequest {
+    /// Handle from `OpenResponse`, opened for writing
+    pub handle: u32,
+    /// Byte offset to write at
+    pub offset: u64,
+    /// Bytes to write
+    #[serde(default)]
+    pub data: Vec<u8>,
+    /// Write this many bytes from the start of the handle's shared memory
+    /// region instead of `data`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shm_len: Option<u64>,
+}
+
+/// Write at offset response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WriteAtResponse {
+    /// Result containing the file size after the write, or error
+    pub result: Result<u64, VfsError>,
+}
+
+/// Attach shared memory request.
+///
+/// Sent with the region's Memory capability, which the VFS service maps
+/// for the life of the handle (or until another region is attached). Map
+/// the region on the client side before sending, since the capability
+/// moves with the message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttachShmRequest {
+    /// Handle from `OpenResponse`
+    pub handle: u32,
+    /// Bytes of the region to map
+    pub size: u32,
+}
+
+/// Attach shared memory response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttachShmResponse {
+    /// Result containing the mapped size (at most the region's) or error
+    pub result: Result<u32, VfsError>,
+}
+
+/// Close handle request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CloseRequest {
+    /// Handle to close
+    pub handle: u32,
+}
+
+/// Close handle response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CloseResponse {
+    /// Result of operation
+    pub result: Result<(), VfsError>,
+}
+
+// ============================================================================
+// Watch Request/Response Types
+// ============================================================================
+
+/// Watch request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatchRequest {
+    /// Path to watch; it must exist
+    pub path: String,
+    /// Also report changes anywhere below a watched directory, not just
+    /// its direct children
+    #[serde(default)]
+    pub recursive: bool,
+}
+
+/// Watch response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatchResponse {
+    /// Result containing the watch ID or error
+    pub result: Result<u32, VfsError>,
+}
+
+/// Unwatch request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnwatchRequest {
+    /// Watch ID from `WatchResponse`
+    pub id: u32,
+}
+
+/// Unwatch response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnwatchResponse {
+    /// Result of operation
+    pub result: Result<(), VfsError>,
+}
+
+/// Kind of change reported to a watch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VfsEventKind {
+    /// A directory was made, or a file was created by an open
+    Create,
+    /// A file's content was written (a plain write may also have created it)
+    Write,
+    /// A file or directory was removed
+    Delete,
+    /// An entry moved from `path` to `new_path`
+    Rename,
+}
+
+/// Change notification sent to a watcher (MSG_VFS_EVENT).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VfsEvent {
+    /// Watch the event matched
+    pub watch_id: u32,
+    /// What happened
+    pub kind: VfsEventKind,
+    /// Path that changed
+    pub path: String,
+    /// Destination of a rename
+    #[serde(default)]
+    pub new_path: Option<String>,
+}
+
+// ============================================================================
+// Mount Request/Response Types
+// ============================================================================
+
+/// Mount request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MountRequest {
+    /// Path prefix to mount
+    pub path: String,
+    /// Backend to serve it
+    pub backend: MountBackend,
+    /// Refuse every change under the mount (always set for assets)
+    #[serde(default)]
+    pub read_only: bool,
+    /// Files of an `Assets` mount, as absolute paths below `path`
+    #[serde(default)]
+    pub files: Vec<(String, Vec<u8>)>,
+    /// Bytes a `Memory` mount holds before evicting its least recently
+    /// used files
+    #[serde(default)]
+    pub size_limit: Option<u64>,
+    /// Change the size limit of the existing `Memory` mount at `path`,
+    /// keeping its files, instead of mounting anew
+    #[serde(default)]
+    pub remount: bool,
+}
+
+/// Mount response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MountResponse {
+    /// Result of operation
+    pub result: Result<(), VfsError>,
+}
+
+/// Set caller user request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetCallerUserRequest {
+    /// User applications act as, or `None` when nobody is signed in
+    #[serde(default)]
+    pub user_id: Option<UserId>,
+}
+
+/// Set caller user response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetCallerUserResponse {
+    /// Result of operation
+    pub result: Result<(), VfsError>,
+}
+
+/// Umount request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UmountRequest {
+    /// Path of the mount to remove
+    pub path: String,
+}
+
+/// Umount response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UmountResponse {
+    /// Result of operation
+    pub result: Result<(), VfsError>,
+}
+
+/// Mount list response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MountsResponse {
+    /// Every mount, sorted by path
+    pub result: Result<Vec<MountPoint>, VfsError>,
+}
+
 // ============================================================================
 // Metadata Request/Response Types
 // ============================================================================
@@ -175,6 +464,32 @@ pub struct ExistsResponse {
     pub result: Result<bool, VfsError>,
 }
 
+/// Hash file request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HashRequest {
+    /// File to hash
+    pub path: String,
+}
+
+/// SHA-256 of a file's content.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileHash {
+    /// Lowercase hex digest
+    pub sha256: String,
+    /// Bytes hashed
+    pub size: u64,
+}
+
+/// Hash file response.
+///
+/// Fails with a `ChunkCorrupt` storage error if the file's inode records a
+/// content hash and the content no longer matches it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HashResponse {
+    /// Result containing the hash, or error
+    pub result: Result<FileHash, VfsError>,
+}
+
 /// Change permissions request.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChmodRequest {
@@ -207,6 +522,211 @@ pub struct ChownResponse {
     pub result: Result<(), VfsError>,
 }
 
+// ============================================================================
+// Extended Attribute Request/Response Types
+// ============================================================================
+
+/// Set extended attribute request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetXattrRequest {
+    /// Path of the file or directory
+    pub path: String,
+    /// Attribute name
+    pub name: String,
+    /// New value, or `None` to remove the attribute
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+/// Set extended attribute response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetXattrResponse {
+    /// Result of operation
+    pub result: Result<(), VfsError>,
+}
+
+/// Get extended attribute request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetXattrRequest {
+    /// Path of the file or directory
+    pub path: String,
+    /// Attribute name
+    pub name: String,
+}
+
+/// Get extended attribute response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetXattrResponse {
+    /// Result containing the value (`None` if the attribute isn't set) or error
+    pub result: Result<Option<String>, VfsError>,
+}
+
+/// List extended attributes request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ListXattrRequest {
+    /// Path of the file or directory
+    pub path: String,
+}
+
+/// List extended attributes response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ListXattrResponse {
+    /// Result containing the attribute names, sorted, or error
+    pub result: Result<Vec<String>, VfsError>,
+}
+
+// ============================================================================
+// Trash Request/Response Types
+// ============================================================================
+
+/// A file or directory in a user's trash.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// Name of the entry in the trash directory
+    pub id: String,
+    /// Path the entry was unlinked from
+    pub original_path: String,
+    /// When it was trashed (wall-clock ms)
+    pub deleted_at: u64,
+    /// File size in bytes (0 for a directory)
+    pub size: u64,
+}
+
+/// List trash request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashListRequest {
+    /// User whose trash to list
+    pub user_id: UserId,
+}
+
+/// List trash response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashListResponse {
+    /// Result containing the trashed files, newest first, or error
+    pub result: Result<Vec<TrashEntry>, VfsError>,
+}
+
+/// Restore from trash request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashRestoreRequest {
+    /// User whose trash holds the file
+    pub user_id: UserId,
+    /// Entry to restore
+    pub id: String,
+    /// Path to restore to, instead of the one it was unlinked from
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+/// Restore from trash response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashRestoreResponse {
+    /// Result containing the path the file was restored to, or error
+    pub result: Result<String, VfsError>,
+}
+
+/// Purge trash request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashPurgeRequest {
+    /// User whose trash to purge
+    pub user_id: UserId,
+    /// Entry to purge, or `None` to empty the trash
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+/// Purge trash response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashPurgeResponse {
+    /// Result containing the number of entries deleted, or error
+    pub result: Result<u32, VfsError>,
+}
+
+// ============================================================================
+// Snapshot Request/Response Types
+// ============================================================================
+
+/// A snapshot of a directory tree.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    /// Snapshot ID, unique per user
+    pub id: String,
+    /// Directory the snapshot was taken of
+    pub root: String,
+    /// Label given at creation
+    #[serde(default)]
+    pub label: Option<String>,
+    /// When it was taken (wall-clock ms)
+    pub created_at: u64,
+    /// Number of files in it
+    pub files: u32,
+    /// Total size of those files in bytes
+    pub size: u64,
+}
+
+/// Create snapshot request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotCreateRequest {
+    /// Directory to snapshot (a home directory or a directory under one)
+    pub path: String,
+    /// Label to show the snapshot by
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Create snapshot response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotCreateResponse {
+    /// Result containing the new snapshot, or error
+    pub result: Result<SnapshotInfo, VfsError>,
+}
+
+/// List snapshots request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotListRequest {
+    /// User whose snapshots to list
+    pub user_id: UserId,
+}
+
+/// List snapshots response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotListResponse {
+    /// Result containing the snapshots, oldest first, or error
+    pub result: Result<Vec<SnapshotInfo>, VfsError>,
+}
+
+/// Restore snapshot request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotRestoreRequest {
+    /// User the snapshot belongs to
+    pub user_id: UserId,
+    /// Snapshot to roll its tree back to
+    pub id: String,
+}
+
+/// Restore snapshot response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotRestoreResponse {
+    /// Result of the operation
+    pub result: Result<(), VfsError>,
+}
+
+/// Delete snapshot request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotDeleteRequest {
+    /// User the snapshot belongs to
+    pub user_id: UserId,
+    /// Snapshot to delete
+    pub id: String,
+}
+
+/// Delete snapshot response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotDeleteResponse {
+    /// Result of the operation
+    pub result: Result<(), VfsError>,
+}
+
 // ============================================================================
 // Quota Request/Response Types
 // ============================================================================
@@ -239,6 +759,178 @@ pub struct GetQuotaResponse {
     pub result: Result<StorageQuota, VfsError>,
 }
 
+/// Quota stat request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuotaStatRequest {
+    /// User whose usage to report
+    pub user_id: UserId,
+}
+
+/// Quota stat response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuotaStatResponse {
+    /// The user's quota with their current usage, or error
+    pub result: Result<StorageQuota, VfsError>,
+}
+
+// ============================================================================
+// Signed Request Types
+// ============================================================================
+
+/// Register (pin) a service's public signing key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegisterServiceKeyRequest {
+    /// Service name the key signs for (e.g. "permission")
+    pub service: String,
+    /// Hex-encoded Ed25519 public key
+    pub public_key: String,
+}
+
+/// Register service key response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegisterServiceKeyResponse {
+    /// Result of operation
+    pub result: Result<(), VfsError>,
+}
+
+// ============================================================================
+// Batch Request/Response Types
+// ============================================================================
+
+/// One operation of a batch: the operation's own request, tagged with its
+/// name (e.g. `{"op": "stat", "path": "/home/1"}`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOp {
+    Mkdir(MkdirRequest),
+    Rmdir(RmdirRequest),
+    Readdir(ReaddirRequest),
+    Write(WriteFileRequest),
+    Read(ReadFileRequest),
+    Unlink(UnlinkRequest),
+    Rename(RenameRequest),
+    Stat(StatRequest),
+    Exists(ExistsRequest),
+}
+
+impl BatchOp {
+    /// Tag the operation is sent with on its own.
+    pub fn request_tag(&self) -> u32 {
+        match self {
+            BatchOp::Mkdir(_) => vfs_msg::MSG_VFS_MKDIR,
+            BatchOp::Rmdir(_) => vfs_msg::MSG_VFS_RMDIR,
+            BatchOp::Readdir(_) => vfs_msg::MSG_VFS_READDIR,
+            BatchOp::Write(_) => vfs_msg::MSG_VFS_WRITE,
+            BatchOp::Read(_) => vfs_msg::MSG_VFS_READ,
+            BatchOp::Unlink(_) => vfs_msg::MSG_VFS_UNLINK,
+            BatchOp::Rename(_) => vfs_msg::MSG_VFS_RENAME,
+            BatchOp::Stat(_) => vfs_msg::MSG_VFS_STAT,
+            BatchOp::Exists(_) => vfs_msg::MSG_VFS_EXISTS,
+        }
+    }
+
+    /// Payload the operation is sent with on its own.
+    pub fn request_data(&self) -> Vec<u8> {
+        let data = match self {
+            BatchOp::Mkdir(r) => serde_json::to_vec(r),
+            BatchOp::Rmdir(r) => serde_json::to_vec(r),
+            BatchOp::Readdir(r) => serde_json::to_vec(r),
+            BatchOp::Write(r) => serde_json::to_vec(r),
+            BatchOp::Read(r) => serde_json::to_vec(r),
+            BatchOp::Unlink(r) => serde_json::to_vec(r),
+            BatchOp::Rename(r) => serde_json::to_vec(r),
+            BatchOp::Stat(r) => serde_json::to_vec(r),
+            BatchOp::Exists(r) => serde_json::to_vec(r),
+        };
+        data.unwrap_or_default()
+    }
+}
+
+/// Batch request.
+///
+/// Operations run concurrently, in no set order: a batch suits independent
+/// operations such as the stats of a directory scan, not a create followed
+/// by a write into it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchRequest {
+    /// Operations to run (at most `MAX_BATCH_OPS`)
+    pub ops: Vec<BatchOp>,
+}
+
+/// Result of one operation of a batch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchEntry {
+    /// Tag of the operation's response
+    pub tag: u32,
+    /// The response JSON, as the operation would get it on its own; each
+    /// entry succeeds or fails by itself
+    pub response: String,
+}
+
+/// Batch response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchResponse {
+    /// One entry per operation, in request order, or an error if the batch
+    /// as a whole was refused or lost
+    pub result: Result<Vec<BatchEntry>, VfsError>,
+}
+
+// ============================================================================
+// Service Counters
+// ============================================================================
+
+/// Snapshot of VfsService's counters of storage operations that never got
+/// their result, and of what garbage collection reclaimed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VfsStats {
+    /// Storage operations awaiting a result
+    pub pending_ops: u32,
+    /// Operations whose result did not arrive in time; the client was
+    /// answered with a timeout error
+    pub timed_out: u64,
+    /// Operations dropped once their request's deadline had passed
+    pub past_deadline: u64,
+    /// Stuck operations the watchdog asked the storage HAL to abort
+    pub aborted: u64,
+    /// Orphaned content records and blobs deleted by garbage collection
+    pub reclaimed_records: u64,
+    /// Bytes those records held, including the chunks they named
+    pub reclaimed_bytes: u64,
+}
+
+impl VfsStats {
+    /// Serialize to JSON bytes.
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Parse from JSON bytes.
+    pub fn from_json(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+}
+
+// ============================================================================
+// Retry Detection
+// ============================================================================
+
+/// Any VFS response, with the success payload left unparsed.
+#[derive(Deserialize)]
+struct AnyResponse {
+    result: Result<serde::de::IgnoredAny, VfsError>,
+}
+
+/// Whether a VFS response is the `Retry` error sent while the service
+/// drains before a restart.
+///
+/// Suitable as the retry check of a `zos_process::ServiceClient`.
+pub fn is_retry_response(msg: &zos_process::ReceivedMessage) -> bool {
+    matches!(
+        serde_json::from_slice::<AnyResponse>(&msg.data),
+        Ok(AnyResponse { result: Err(ref e) }) if e.is_retry()
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -263,4 +955,80 @@ mod tests {
         assert_eq!(req.path, "/home/user/Documents");
         assert!(req.create_parents);
     }
+
+    #[test]
+    fn test_is_retry_response() {
+        let reply = |response: &ReadFileResponse| zos_process::ReceivedMessage {
+            from_pid: 3,
+            tag: vfs_msg::MSG_VFS_READ_RESPONSE,
+            cap_slots: Vec::new(),
+            correlation_id: None,
+            data: serde_json::to_vec(response).unwrap(),
+        };
+        let draining = ReadFileResponse {
+            result: Err(VfsError::retry("draining")),
+        };
+        let missing = ReadFileResponse {
+            result: Err(VfsError::NotFound),
+        };
+        assert!(is_retry_response(&reply(&draining)));
+        assert!(!is_retry_response(&reply(&missing)));
+    }
+
+    #[test]
+    fn test_batch_op_round_trip() {
+        let json = br#"{"ops":[{"op":"stat","path":"/a"},{"op":"read","path":"/b","offset":null,"length":4}]}"#;
+        let request: BatchRequest = serde_json::from_slice(json).unwrap();
+        assert_eq!(request.ops.len(), 2);
+        assert_eq!(request.ops[0].request_tag(), vfs_msg::MSG_VFS_STAT);
+
+        // The payload is the operation's own request
+        let read: ReadFileRequest = serde_json::from_slice(&request.ops[1].request_data()).unwrap();
+        assert_eq!(read.path, "/b");
+        assert_eq!(read.length, Some(4));
+
+        let again: BatchRequest =
+            serde_json::from_slice(&serde_json::to_vec(&request).unwrap()).unwrap();
+        assert!(matches!(&again.ops[0], BatchOp::Stat(r) if r.path == "/a"));
+    }
+
+    #[test]
+    fn test_rmdir_response_failures() {
+        // Responses without the failure list still parse
+        let plain: RmdirResponse = serde_json::from_str(r#"{"result":{"Ok":null}}"#).unwrap();
+        assert!(plain.result.is_ok());
+        assert!(plain.failed.is_empty());
+
+        let partial = RmdirResponse {
+            result: Err(VfsError::DirectoryNotEmpty),
+            failed: alloc::vec![RmdirFailure {
+                path: String::from("/tmp/dir/locked"),
+                error: VfsError::PermissionDenied,
+            }],
+        };
+        let parsed: RmdirResponse =
+            serde_json::from_slice(&serde_json::to_vec(&partial).unwrap()).unwrap();
+        assert_eq!(parsed.failed.len(), 1);
+        assert_eq!(parsed.failed[0].path, "/tmp/dir/locked");
+        assert!(matches!(parsed.failed[0].error, VfsError::PermissionDenied));
+    }
+
+    #[test]
+    fn test_handle_io_without_shared_memory() {
+        // Requests and responses from before shared memory still parse
+        let read: ReadAtRequest =
+            serde_json::from_str(r#"{"handle":1,"offset":0,"length":4}"#).unwrap();
+        assert!(!read.shm);
+        let write: WriteAtRequest =
+            serde_json::from_str(r#"{"handle":1,"offset":0,"data":[1,2]}"#).unwrap();
+        assert_eq!(write.shm_len, None);
+        let reply: ReadAtResponse = serde_json::from_str(r#"{"result":{"Ok":[7]}}"#).unwrap();
+        assert_eq!(reply.shm_len, None);
+
+        // A write through shared memory carries no data
+        let write: WriteAtRequest =
+            serde_json::from_str(r#"{"handle":1,"offset":8,"shm_len":4096}"#).unwrap();
+        assert!(write.data.is_empty());
+        assert_eq!(write.shm_len, Some(4096));
+    }
 }
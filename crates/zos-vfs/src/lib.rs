@@ -6,6 +6,10 @@
 //! - **Path**: Path validation, normalization, and resolution
 //! - **Service**: VfsService trait for filesystem operations
 //! - **Storage**: Content storage, encryption, and quota management
+//! - **Overlay**: Read-only base layer composed with a writable upper layer
+//! - **Mount**: Path prefixes served by storage, memory or bundled assets
+//! - **Trash**: Where unlinked files wait to be restored or purged
+//! - **Snapshot**: Point-in-time copies of directory trees, for rollback
 //! - **Bootstrap**: Filesystem initialization on first boot
 //! - **IPC**: Inter-process communication protocol for VFS operations
 //!
@@ -14,7 +18,8 @@
 //! 1. **Hierarchical paths**: Unix-like `/path/to/file` semantics
 //! 2. **User-centric**: Each user has an isolated home directory
 //! 3. **Permission-aware**: File access controlled by ownership and permissions
-//! 4. **Encryption-ready**: Support for encrypted file storage
+//! 4. **Encrypted at rest**: Owned file content is stored sealed under a
+//!    per-user key (see [`storage::encryption`])
 //!
 //! # Architecture
 //!
@@ -51,14 +56,26 @@ pub mod service;
 pub mod testing;
 
 pub mod bootstrap;
+pub mod mount;
+pub mod overlay;
+pub mod schema;
+pub mod snapshot;
 pub mod storage;
+pub mod trash;
 
 // Convenient re-exports at crate root
 pub use client::{VfsClient, VFS_ENDPOINT_SLOT, VFS_RESPONSE_SLOT};
 pub use core::{normalize_path, parent_path, validate_path};
-pub use core::{DirEntry, FilePermissions, Inode, InodeType, StorageErrorKind, UserId, VfsError};
+pub use core::{
+    DirEntry, FilePermissions, Inode, InodeType, StorageErrorKind, UserId, VfsError, Xattrs,
+};
 pub use ipc::vfs_msg;
 pub use service::{check_execute, check_read, check_write, PermissionContext, ProcessClass, VfsService};
+pub use mount::{
+    MountBackend, MountPoint, MountTable, MountedFs, DEFAULT_TMP_SIZE_LIMIT, TMP_MOUNT_PATH,
+};
+pub use overlay::OverlayVfs;
+pub use schema::{decode_inode, SchemaError, INODE_SCHEMA_VERSION};
 pub use storage::{StorageQuota, StorageUsage};
 pub use testing::MemoryVfs;
 
@@ -0,0 +1,770 @@
+//! Mount table for the VFS layer.
+//!
+//! Path prefixes can be served by different filesystem backends. The
+//! [`MountTable`] maps each mounted prefix to a [`MountBackend`]; a path is
+//! served by the mount with the longest prefix covering it, so `/` (always
+//! mounted on storage) catches everything no other mount claims.
+//!
+//! | Backend | Storage | Typical use |
+//! |---------|---------|-------------|
+//! | `Storage` | IndexedDB via the storage syscalls | `/`, `/home` |
+//! | `Memory` | [`MountedFs`], lost on restart | `/tmp` |
+//! | `Assets` | [`MountedFs`] built from bundled files, read-only | `/system` |
+//!
+//! Backends see the same global paths as clients: a file at `/tmp/a` on a
+//! memory mount is stored as `/tmp/a` in its [`MemoryVfs`], under a mount
+//! root directory created when the backend is.
+//!
+//! A memory mount may be given a size limit. Writes that would take it past
+//! the limit first evict the least recently used files, so a mount such as
+//! `/tmp` works as scratch space that never fills up; only a single file
+//! larger than the whole limit is refused.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    is_under, normalize_path, parent_path, DirEntry, FilePermissions, Inode, VfsError,
+};
+use crate::service::{check_read, check_write, PermissionContext, VfsService};
+use crate::testing::MemoryVfs;
+
+/// Maximum number of mounts, including `/`.
+pub const MAX_MOUNTS: usize = 32;
+
+/// Path of the memory mount the VFS service creates at startup.
+pub const TMP_MOUNT_PATH: &str = "/tmp";
+
+/// Size limit of the `/tmp` mount unless it is remounted with another.
+pub const DEFAULT_TMP_SIZE_LIMIT: u64 = 32 * 1024 * 1024;
+
+/// Filesystem backend serving a mount.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MountBackend {
+    /// Persistent storage (IndexedDB), served through the storage syscalls
+    Storage,
+    /// In-memory filesystem, empty when mounted and lost on restart
+    Memory,
+    /// Read-only filesystem built from files supplied with the mount
+    Assets,
+}
+
+/// A mounted path prefix.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MountPoint {
+    /// Normalized path the mount covers, along with everything below it
+    pub path: String,
+    /// Backend serving the mount
+    pub backend: MountBackend,
+    /// Refuse every change under the mount
+    #[serde(default)]
+    pub read_only: bool,
+    /// Bytes of file content a memory mount holds before it evicts files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_limit: Option<u64>,
+}
+
+impl MountPoint {
+    /// The root mount every table starts with.
+    pub fn root() -> Self {
+        Self {
+            path: String::from("/"),
+            backend: MountBackend::Storage,
+            read_only: false,
+            size_limit: None,
+        }
+    }
+
+    /// Whether the mount is served in-process rather than by storage.
+    pub fn is_local(&self) -> bool {
+        self.backend != MountBackend::Storage
+    }
+}
+
+/// Mount points, resolved by longest path prefix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MountTable {
+    /// Mounts sorted by path; `/` is always first
+    mounts: Vec<MountPoint>,
+}
+
+impl Default for MountTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MountTable {
+    /// A table holding only the root storage mount.
+    pub fn new() -> Self {
+        Self {
+            mounts: alloc::vec![MountPoint::root()],
+        }
+    }
+
+    /// The mount serving `path`.
+    ///
+    /// Paths that fail to normalize resolve to `/`, whose backend reports
+    /// the error.
+    pub fn resolve(&self, path: &str) -> &MountPoint {
+        self.mounts
+            .iter()
+            .filter(|mount| is_under(path, &mount.path))
+            .max_by_key(|mount| mount.path.len())
+            .unwrap_or(&self.mounts[0])
+    }
+
+    /// The mount at exactly `path`, if any.
+    pub fn get(&self, path: &str) -> Option<&MountPoint> {
+        self.mounts.iter().find(|mount| mount.path == path)
+    }
+
+    /// The mount at exactly `path`, mutably.
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut MountPoint> {
+        self.mounts.iter_mut().find(|mount| mount.path == path)
+    }
+
+    /// Add a mount, normalizing its path.
+    pub fn insert(&mut self, mut mount: MountPoint) -> Result<(), VfsError> {
+        mount.path = normalize_path(&mount.path)?;
+        if mount.path == "/" {
+            return Err(VfsError::InvalidRequest(String::from(
+                "The root mount cannot be replaced",
+            )));
+        }
+        if self.get(&mount.path).is_some() {
+            return Err(VfsError::AlreadyExists);
+        }
+        if self.mounts.len() >= MAX_MOUNTS {
+            return Err(VfsError::InvalidRequest(alloc::format!(
+                "Too many mounts (max {})",
+                MAX_MOUNTS
+            )));
+        }
+        let at = self
+            .mounts
+            .partition_point(|existing| existing.path < mount.path);
+        self.mounts.insert(at, mount);
+        Ok(())
+    }
+
+    /// Remove the mount at `path`.
+    ///
+    /// A mount with other mounts below it stays until they are removed.
+    pub fn remove(&mut self, path: &str) -> Result<MountPoint, VfsError> {
+        let path = normalize_path(path)?;
+        if path == "/" {
+            return Err(VfsError::InvalidRequest(String::from(
+                "The root mount cannot be removed",
+            )));
+        }
+        let index = self
+            .mounts
+            .iter()
+            .position(|mount| mount.path == path)
+            .ok_or(VfsError::NotFound)?;
+        if self
+            .mounts
+            .iter()
+            .any(|mount| mount.path != path && is_under(&mount.path, &path))
+        {
+            return Err(VfsError::InvalidRequest(String::from(
+                "Other mounts are below this one",
+            )));
+        }
+        Ok(self.mounts.remove(index))
+    }
+
+    /// Every mount, sorted by path.
+    pub fn list(&self) -> &[MountPoint] {
+        &self.mounts
+    }
+}
+
+// =============================================================================
+// In-process Filesystems
+// =============================================================================
+
+/// File sizes and recency of use, for a size-limited memory mount.
+#[derive(Debug, Default)]
+struct Usage {
+    /// Bytes of file content held
+    used: u64,
+    /// Incremented on each use, so ties never occur
+    clock: u64,
+    /// File path -> (size, clock at last read or write)
+    files: BTreeMap<String, (u64, u64)>,
+}
+
+impl Usage {
+    /// Record a write of `size` bytes to `path`.
+    fn record(&mut self, path: &str, size: u64) {
+        self.clock += 1;
+        if let Some((old, _)) = self.files.insert(String::from(path), (size, self.clock)) {
+            self.used -= old;
+        }
+        self.used += size;
+    }
+
+    /// Mark `path` as just used.
+    fn touch(&mut self, path: &str) {
+        self.clock += 1;
+        if let Some(file) = self.files.get_mut(path) {
+            file.1 = self.clock;
+        }
+    }
+
+    /// Forget `path` and every file below it.
+    fn forget(&mut self, path: &str) {
+        let gone: Vec<String> = self
+            .files
+            .keys()
+            .filter(|file| is_under(file, path))
+            .cloned()
+            .collect();
+        for file in gone {
+            if let Some((size, _)) = self.files.remove(&file) {
+                self.used -= size;
+            }
+        }
+    }
+
+    /// Move the files at and below `from` to `to`.
+    fn rename(&mut self, from: &str, to: &str) {
+        let moved: Vec<String> = self
+            .files
+            .keys()
+            .filter(|file| is_under(file, from))
+            .cloned()
+            .collect();
+        for file in moved {
+            if let Some(entry) = self.files.remove(&file) {
+                let new_path = alloc::format!("{}{}", to, &file[from.len()..]);
+                self.files.insert(new_path, entry);
+            }
+        }
+    }
+
+    /// The least recently used file other than `keep`.
+    fn oldest_except(&self, keep: &str) -> Option<String> {
+        self.files
+            .iter()
+            .filter(|(file, _)| file.as_str() != keep)
+            .min_by_key(|(_, (_, last_use))| *last_use)
+            .map(|(file, _)| file.clone())
+    }
+}
+
+/// A memory or asset mount, served in-process.
+///
+/// Operations apply the same permission checks as storage-backed paths.
+/// Entries created here take the permissions of the directory they are
+/// created in and are owned by the caller's user, so a world-writable
+/// mount root (as `/tmp` has) stays usable by every process.
+///
+/// Eviction is the exception: when a write needs room under the size
+/// limit, files are removed regardless of who owns them. Directories are
+/// left in place, empty.
+pub struct MountedFs {
+    /// Entries, stored under their global paths
+    vfs: MemoryVfs,
+    /// Path of the mount root
+    root: String,
+    /// Refuse every change
+    read_only: bool,
+    /// Bytes of file content held before files are evicted
+    size_limit: Option<u64>,
+    /// Sizes and last use of the files held
+    usage: RefCell<Usage>,
+}
+
+impl MountedFs {
+    /// An empty, writable filesystem for a memory mount at `root`, holding
+    /// up to `size_limit` bytes of file content if given.
+    ///
+    /// The mount root is world-writable.
+    pub fn memory(root: &str, size_limit: Option<u64>) -> Result<Self, VfsError> {
+        let root = normalize_path(root)?;
+        let vfs = MemoryVfs::new();
+        vfs.mkdir_p(&root)?;
+        vfs.chmod(&root, FilePermissions::world_rw())?;
+        Ok(Self {
+            vfs,
+            root,
+            read_only: false,
+            size_limit,
+            usage: RefCell::new(Usage::default()),
+        })
+    }
+
+    /// A read-only filesystem for an asset mount at `root`, holding `files`.
+    ///
+    /// File paths are absolute and must lie below `root`; directories are
+    /// created as needed. Everything is readable by every process.
+    pub fn assets(root: &str, files: &[(String, Vec<u8>)]) -> Result<Self, VfsError> {
+        let root = normalize_path(root)?;
+        let vfs = MemoryVfs::new();
+        vfs.mkdir_p(&root)?;
+        for (path, content) in files {
+            let path = normalize_path(path)?;
+            if path == root || !is_under(&path, &root) {
+                return Err(VfsError::InvalidPath(alloc::format!(
+                    "Asset {} is outside the mount",
+                    path
+                )));
+            }
+            vfs.mkdir_p(&parent_path(&path))?;
+            vfs.write_file(&path, content)?;
+        }
+        Self::seal(&vfs, &root)?;
+        Ok(Self {
+            vfs,
+            root,
+            read_only: true,
+            size_limit: None,
+            usage: RefCell::new(Usage::default()),
+        })
+    }
+
+    /// Make `path` and everything below it readable by all, writable by none.
+    fn seal(vfs: &MemoryVfs, path: &str) -> Result<(), VfsError> {
+        vfs.chmod(path, FilePermissions::read_only())?;
+        if vfs.stat(path)?.is_directory() {
+            for entry in vfs.readdir(path)? {
+                Self::seal(vfs, &entry.path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the timestamp recorded on entries changed from now on.
+    pub fn set_now(&self, now_ms: u64) {
+        self.vfs.set_now(now_ms);
+    }
+
+    /// Bytes of file content held.
+    pub fn used(&self) -> u64 {
+        self.usage.borrow().used
+    }
+
+    /// Change the size limit, evicting files until they fit under it.
+    pub fn set_size_limit(&mut self, size_limit: Option<u64>) -> Result<(), VfsError> {
+        self.check_writable()?;
+        self.size_limit = size_limit;
+        self.make_room("", 0)
+    }
+
+    /// Evict least recently used files until replacing `path` with `size`
+    /// bytes fits under the size limit.
+    fn make_room(&self, path: &str, size: u64) -> Result<(), VfsError> {
+        let Some(limit) = self.size_limit else {
+            return Ok(());
+        };
+        if size > limit {
+            return Err(VfsError::FileTooLarge);
+        }
+        let mut usage = self.usage.borrow_mut();
+        let current = usage.files.get(path).map_or(0, |(old, _)| *old);
+        while usage.used - current + size > limit {
+            let Some(victim) = usage.oldest_except(path) else {
+                break;
+            };
+            self.vfs.unlink(&victim)?;
+            usage.forget(&victim);
+        }
+        Ok(())
+    }
+
+    /// Fail unless the mount takes changes.
+    fn check_writable(&self) -> Result<(), VfsError> {
+        if self.read_only {
+            return Err(VfsError::PermissionDenied);
+        }
+        Ok(())
+    }
+
+    /// Fail with `PermissionDenied` unless `ctx` may write to `inode`.
+    fn require_write(inode: &Inode, ctx: &PermissionContext) -> Result<(), VfsError> {
+        if !check_write(inode, ctx) {
+            return Err(VfsError::PermissionDenied);
+        }
+        Ok(())
+    }
+
+    /// The directory `path` would be created in, checked for write access.
+    fn writable_parent(&self, path: &str, ctx: &PermissionContext) -> Result<Inode, VfsError> {
+        let parent = self.vfs.stat(&parent_path(path))?;
+        if !parent.is_directory() {
+            return Err(VfsError::NotADirectory);
+        }
+        Self::require_write(&parent, ctx)?;
+        Ok(parent)
+    }
+
+    /// Give a new entry its parent's permissions and the caller as owner.
+    fn adopt(&self, path: &str, parent: &Inode, ctx: &PermissionContext) -> Result<(), VfsError> {
+        self.vfs.chmod(path, parent.permissions.clone())?;
+        self.vfs.chown(path, ctx.user_id)
+    }
+
+    /// Whether `path` is the mount root, which can't be removed or moved.
+    fn is_root(&self, path: &str) -> bool {
+        path == self.root
+    }
+
+    // ========== Operations ==========
+
+    /// Create a directory, and with `create_parents` any missing ancestors.
+    pub fn mkdir(
+        &self,
+        path: &str,
+        create_parents: bool,
+        ctx: &PermissionContext,
+    ) -> Result<(), VfsError> {
+        let path = normalize_path(path)?;
+        self.check_writable()?;
+        if let Ok(existing) = self.vfs.stat(&path) {
+            return match existing.is_directory() && create_parents {
+                true => Ok(()),
+                false => Err(VfsError::AlreadyExists),
+            };
+        }
+
+        // Directories to create, outermost first
+        let mut missing = alloc::vec![path.clone()];
+        if create_parents {
+            let mut ancestor = parent_path(&path);
+            while !self.vfs.exists(&ancestor)? {
+                missing.push(ancestor.clone());
+                ancestor = parent_path(&ancestor);
+            }
+        }
+        for dir in missing.iter().rev() {
+            let parent = self.writable_parent(dir, ctx)?;
+            self.vfs.mkdir(dir)?;
+            self.adopt(dir, &parent, ctx)?;
+        }
+        Ok(())
+    }
+
+    /// Remove a directory, and with `recursive` everything below it.
+    pub fn rmdir(
+        &self,
+        path: &str,
+        recursive: bool,
+        ctx: &PermissionContext,
+    ) -> Result<(), VfsError> {
+        let path = normalize_path(path)?;
+        self.check_writable()?;
+        if self.is_root(&path) {
+            return Err(VfsError::InvalidRequest(String::from(
+                "Cannot remove a mount point",
+            )));
+        }
+        let inode = self.vfs.stat(&path)?;
+        if !inode.is_directory() {
+            return Err(VfsError::NotADirectory);
+        }
+        Self::require_write(&inode, ctx)?;
+        if recursive {
+            self.vfs.rmdir_recursive(&path)?;
+        } else {
+            self.vfs.rmdir(&path)?;
+        }
+        self.usage.borrow_mut().forget(&path);
+        Ok(())
+    }
+
+    /// List a directory.
+    pub fn readdir(&self, path: &str, ctx: &PermissionContext) -> Result<Vec<DirEntry>, VfsError> {
+        let inode = self.vfs.stat(path)?;
+        if !inode.is_directory() {
+            return Err(VfsError::NotADirectory);
+        }
+        if !check_read(&inode, ctx) {
+            return Err(VfsError::PermissionDenied);
+        }
+        self.vfs.readdir(path)
+    }
+
+    /// Create or replace a file.
+    ///
+    /// A replaced file keeps its permissions and owner.
+    pub fn write_file(
+        &self,
+        path: &str,
+        content: &[u8],
+        ctx: &PermissionContext,
+    ) -> Result<(), VfsError> {
+        let path = normalize_path(path)?;
+        self.check_writable()?;
+        match self.vfs.stat(&path) {
+            Ok(existing) => {
+                if !existing.is_file() {
+                    return Err(VfsError::NotAFile);
+                }
+                Self::require_write(&existing, ctx)?;
+                self.make_room(&path, content.len() as u64)?;
+                self.vfs.write_file(&path, content)?;
+                self.vfs.chmod(&path, existing.permissions)?;
+                self.vfs.chown(&path, existing.owner_id)?;
+            }
+            Err(VfsError::NotFound) => {
+                let parent = self.writable_parent(&path, ctx)?;
+                self.make_room(&path, content.len() as u64)?;
+                self.vfs.write_file(&path, content)?;
+                self.adopt(&path, &parent, ctx)?;
+            }
+            Err(e) => return Err(e),
+        }
+        self.usage.borrow_mut().record(&path, content.len() as u64);
+        Ok(())
+    }
+
+    /// Read a file, or the `length` bytes from `offset` if given.
+    pub fn read_file(
+        &self,
+        path: &str,
+        offset: Option<u64>,
+        length: Option<u64>,
+        ctx: &PermissionContext,
+    ) -> Result<Vec<u8>, VfsError> {
+        let inode = self.vfs.stat(path)?;
+        if !inode.is_file() {
+            return Err(VfsError::NotAFile);
+        }
+        if !check_read(&inode, ctx) {
+            return Err(VfsError::PermissionDenied);
+        }
+        let content = self.vfs.read_file(path)?;
+        self.usage.borrow_mut().touch(path);
+        let start = offset.map_or(0, |o| o.min(content.len() as u64) as usize);
+        let end = length.map_or(content.len(), |l| {
+            (start as u64).saturating_add(l).min(content.len() as u64) as usize
+        });
+        Ok(content[start..end].to_vec())
+    }
+
+    /// Delete a file.
+    pub fn unlink(&self, path: &str, ctx: &PermissionContext) -> Result<(), VfsError> {
+        self.check_writable()?;
+        let inode = self.vfs.stat(path)?;
+        if !inode.is_file() {
+            return Err(VfsError::NotAFile);
+        }
+        Self::require_write(&inode, ctx)?;
+        self.vfs.unlink(path)?;
+        self.usage.borrow_mut().forget(path);
+        Ok(())
+    }
+
+    /// Move a file or directory within the mount.
+    pub fn rename(&self, from: &str, to: &str, ctx: &PermissionContext) -> Result<(), VfsError> {
+        let from = normalize_path(from)?;
+        let to = normalize_path(to)?;
+        self.check_writable()?;
+        if self.is_root(&from) || self.is_root(&to) {
+            return Err(VfsError::InvalidRequest(String::from(
+                "Cannot move a mount point",
+            )));
+        }
+        let inode = self.vfs.stat(&from)?;
+        Self::require_write(&inode, ctx)?;
+        if self.vfs.exists(&to)? {
+            return Err(VfsError::AlreadyExists);
+        }
+        self.writable_parent(&to, ctx)?;
+        self.vfs.rename(&from, &to)?;
+        self.usage.borrow_mut().rename(&from, &to);
+        Ok(())
+    }
+
+    /// Get an entry's metadata.
+    pub fn stat(&self, path: &str) -> Result<Inode, VfsError> {
+        self.vfs.stat(path)
+    }
+
+    /// Check whether an entry exists.
+    pub fn exists(&self, path: &str) -> Result<bool, VfsError> {
+        self.vfs.exists(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::ProcessClass;
+
+    fn app() -> PermissionContext {
+        PermissionContext {
+            user_id: None,
+            process_class: ProcessClass::Application,
+        }
+    }
+
+    fn mount(path: &str, backend: MountBackend) -> MountPoint {
+        MountPoint {
+            path: String::from(path),
+            backend,
+            read_only: false,
+            size_limit: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_longest_prefix() {
+        let mut table = MountTable::new();
+        table.insert(mount("/tmp", MountBackend::Memory)).unwrap();
+        table
+            .insert(mount("/tmp/cache", MountBackend::Storage))
+            .unwrap();
+
+        assert_eq!(table.resolve("/tmp").path, "/tmp");
+        assert_eq!(table.resolve("/tmp/a/b").path, "/tmp");
+        assert_eq!(table.resolve("/tmp/cache/x").path, "/tmp/cache");
+        assert_eq!(table.resolve("/tmpfile").path, "/");
+        assert_eq!(table.resolve("/tmp/../home").path, "/");
+        assert_eq!(table.resolve("relative").path, "/");
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut table = MountTable::new();
+        assert!(table.insert(mount("/", MountBackend::Memory)).is_err());
+        table.insert(mount("/tmp/", MountBackend::Memory)).unwrap();
+        assert!(matches!(
+            table.insert(mount("/tmp", MountBackend::Storage)),
+            Err(VfsError::AlreadyExists)
+        ));
+        table.insert(mount("/tmp/a", MountBackend::Memory)).unwrap();
+        assert_eq!(table.list().len(), 3);
+
+        // Nested mounts go first
+        assert!(table.remove("/tmp").is_err());
+        table.remove("/tmp/a").unwrap();
+        assert_eq!(table.remove("/tmp").unwrap().backend, MountBackend::Memory);
+        assert!(matches!(table.remove("/tmp"), Err(VfsError::NotFound)));
+        assert!(table.remove("/").is_err());
+        assert_eq!(table.list(), &[MountPoint::root()]);
+    }
+
+    #[test]
+    fn test_memory_mount_permissions() {
+        let fs = MountedFs::memory("/tmp", None).unwrap();
+        let ctx = app();
+
+        fs.mkdir("/tmp/a/b", true, &ctx).unwrap();
+        fs.write_file("/tmp/a/b/f", b"hello", &ctx).unwrap();
+        assert_eq!(
+            fs.read_file("/tmp/a/b/f", Some(1), Some(3), &ctx).unwrap(),
+            b"ell"
+        );
+        assert_eq!(
+            fs.read_file("/tmp/a/b/f", Some(9), None, &ctx).unwrap(),
+            b""
+        );
+        assert_eq!(fs.readdir("/tmp/a", &ctx).unwrap().len(), 1);
+
+        // Created entries inherit the mount root's world access
+        assert!(fs.stat("/tmp/a/b/f").unwrap().permissions.world_write);
+
+        fs.rename("/tmp/a/b/f", "/tmp/g", &ctx).unwrap();
+        assert!(!fs.exists("/tmp/a/b/f").unwrap());
+        fs.unlink("/tmp/g", &ctx).unwrap();
+        fs.rmdir("/tmp/a", true, &ctx).unwrap();
+        assert!(fs.rmdir("/tmp", false, &ctx).is_err());
+        assert!(fs.rename("/tmp", "/tmp2", &ctx).is_err());
+    }
+
+    #[test]
+    fn test_replaced_file_keeps_owner() {
+        let fs = MountedFs::memory("/tmp", None).unwrap();
+        let owner = PermissionContext {
+            user_id: Some(7),
+            process_class: ProcessClass::Application,
+        };
+        fs.write_file("/tmp/f", b"1", &owner).unwrap();
+        fs.write_file("/tmp/f", b"22", &app()).unwrap();
+
+        let inode = fs.stat("/tmp/f").unwrap();
+        assert_eq!(inode.owner_id, Some(7));
+        assert_eq!(inode.size, 2);
+    }
+
+    #[test]
+    fn test_size_limit_evicts_least_recently_used() {
+        let fs = MountedFs::memory("/tmp", Some(10)).unwrap();
+        let ctx = app();
+        fs.mkdir("/tmp/d", false, &ctx).unwrap();
+        fs.write_file("/tmp/a", b"aaaa", &ctx).unwrap();
+        fs.write_file("/tmp/d/b", b"bbbb", &ctx).unwrap();
+        // Reading `a` makes `b` the oldest
+        fs.read_file("/tmp/a", None, None, &ctx).unwrap();
+
+        fs.write_file("/tmp/c", b"cccc", &ctx).unwrap();
+        assert!(!fs.exists("/tmp/d/b").unwrap());
+        assert!(fs.exists("/tmp/d").unwrap());
+        assert!(fs.exists("/tmp/a").unwrap());
+        assert_eq!(fs.used(), 8);
+
+        // Growing a file in place evicts others, never itself
+        fs.write_file("/tmp/c", b"cccccccccc", &ctx).unwrap();
+        assert!(!fs.exists("/tmp/a").unwrap());
+        assert_eq!(fs.used(), 10);
+        assert!(matches!(
+            fs.write_file("/tmp/big", &[0; 11], &ctx),
+            Err(VfsError::FileTooLarge)
+        ));
+
+        // Moved and removed files are accounted for
+        fs.rename("/tmp/c", "/tmp/d/c", &ctx).unwrap();
+        fs.write_file("/tmp/e", b"e", &ctx).unwrap();
+        assert!(!fs.exists("/tmp/d/c").unwrap());
+        fs.rmdir("/tmp/d", true, &ctx).unwrap();
+        assert_eq!(fs.used(), 1);
+    }
+
+    #[test]
+    fn test_lowering_size_limit_evicts() {
+        let mut fs = MountedFs::memory("/tmp", None).unwrap();
+        let ctx = app();
+        fs.write_file("/tmp/a", &[0; 100], &ctx).unwrap();
+        fs.write_file("/tmp/b", &[0; 100], &ctx).unwrap();
+        assert_eq!(fs.used(), 200);
+
+        fs.set_size_limit(Some(150)).unwrap();
+        assert!(!fs.exists("/tmp/a").unwrap());
+        assert!(fs.exists("/tmp/b").unwrap());
+        assert_eq!(fs.used(), 100);
+    }
+
+    #[test]
+    fn test_assets_are_read_only() {
+        let files = alloc::vec![
+            (String::from("/system/apps/clock.json"), b"{}".to_vec()),
+            (String::from("/system/version"), b"1".to_vec()),
+        ];
+        let fs = MountedFs::assets("/system", &files).unwrap();
+        let ctx = app();
+
+        assert_eq!(
+            fs.read_file("/system/version", None, None, &ctx).unwrap(),
+            b"1"
+        );
+        assert_eq!(fs.readdir("/system/apps", &ctx).unwrap().len(), 1);
+        assert!(matches!(
+            fs.write_file("/system/version", b"2", &PermissionContext::system()),
+            Err(VfsError::PermissionDenied)
+        ));
+        assert!(matches!(
+            fs.unlink("/system/version", &ctx),
+            Err(VfsError::PermissionDenied)
+        ));
+
+        let outside = alloc::vec![(String::from("/etc/passwd"), Vec::new())];
+        assert!(MountedFs::assets("/system", &outside).is_err());
+    }
+}
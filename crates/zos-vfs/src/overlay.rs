@@ -0,0 +1,733 @@
+//! Overlay filesystem for the VFS layer.
+//!
+//! Composes a read-only lower layer (e.g. `/system` shipped with the app
+//! bundle) with a writable upper layer. Lookups prefer the upper layer and
+//! fall through to the lower one; every mutation lands in the upper layer,
+//! so the lower layer is never modified.
+//!
+//! # Whiteouts
+//!
+//! Deleting an entry that exists in the lower layer records a whiteout file
+//! (`.wh.<name>`) next to it in the upper layer. A directory recreated over a
+//! deleted lower directory is marked opaque (`.wh..wh..opq`) so the old lower
+//! contents stay hidden. Both markers are ordinary files in the upper layer,
+//! which keeps them persistent across boots and follows the OCI image layer
+//! convention.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::core::{
+    filename, join_path, normalize_path, parent_path, DirEntry, FilePermissions, Inode, InodeType,
+    UserId, VfsError,
+};
+use crate::service::VfsService;
+use crate::storage::{StorageQuota, StorageUsage};
+
+/// Filename prefix marking a whiteout entry in the upper layer.
+pub const WHITEOUT_PREFIX: &str = ".wh.";
+
+/// Marker file that makes an upper directory hide the lower directory beneath it.
+pub const OPAQUE_MARKER: &str = ".wh..wh..opq";
+
+/// Check if a filename is reserved for overlay bookkeeping.
+pub fn is_whiteout_name(name: &str) -> bool {
+    name.starts_with(WHITEOUT_PREFIX)
+}
+
+/// Path of the whiteout entry recording the deletion of `path`.
+fn whiteout_path(path: &str) -> String {
+    join_path(
+        &parent_path(path),
+        &alloc::format!("{}{}", WHITEOUT_PREFIX, filename(path)),
+    )
+}
+
+/// Path of the opaque marker for directory `dir`.
+fn opaque_path(dir: &str) -> String {
+    join_path(dir, OPAQUE_MARKER)
+}
+
+/// Normalize a path and reject components reserved for whiteouts.
+fn overlay_path(path: &str) -> Result<String, VfsError> {
+    let path = normalize_path(path)?;
+    if path.split('/').any(is_whiteout_name) {
+        return Err(VfsError::InvalidPath(String::from(
+            "Path uses a reserved overlay whiteout name",
+        )));
+    }
+    Ok(path)
+}
+
+/// Which layer currently provides a path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Layer {
+    Upper,
+    Lower,
+}
+
+/// Overlay VFS combining a read-only lower layer and a writable upper layer.
+pub struct OverlayVfs<L: VfsService, U: VfsService> {
+    /// Read-only base layer
+    lower: L,
+    /// Writable layer receiving all modifications and whiteouts
+    upper: U,
+}
+
+impl<L: VfsService, U: VfsService> OverlayVfs<L, U> {
+    /// Create an overlay from a lower (read-only) and upper (writable) layer.
+    pub fn new(lower: L, upper: U) -> Self {
+        Self { lower, upper }
+    }
+
+    /// Get the read-only lower layer.
+    pub fn lower(&self) -> &L {
+        &self.lower
+    }
+
+    /// Get the writable upper layer.
+    pub fn upper(&self) -> &U {
+        &self.upper
+    }
+
+    /// Split the overlay back into its layers.
+    pub fn into_parts(self) -> (L, U) {
+        (self.lower, self.upper)
+    }
+
+    /// Check whether the lower layer is visible at `path`.
+    ///
+    /// The lower entry is hidden if the path or any ancestor has a whiteout,
+    /// or if any ancestor directory in the upper layer is opaque.
+    fn lower_visible(&self, path: &str) -> Result<bool, VfsError> {
+        let mut current = String::from(path);
+        while current != "/" {
+            if self.upper.exists(&whiteout_path(&current))? {
+                return Ok(false);
+            }
+            let parent = parent_path(&current);
+            if self.upper.exists(&opaque_path(&parent))? {
+                return Ok(false);
+            }
+            current = parent;
+        }
+        Ok(true)
+    }
+
+    /// Check whether `path` exists in the lower layer and is not hidden.
+    fn in_lower(&self, path: &str) -> Result<bool, VfsError> {
+        Ok(self.lower_visible(path)? && self.lower.exists(path)?)
+    }
+
+    /// Find the layer providing a normalized path.
+    fn locate(&self, path: &str) -> Result<Option<Layer>, VfsError> {
+        if self.upper.exists(path)? {
+            Ok(Some(Layer::Upper))
+        } else if self.in_lower(path)? {
+            Ok(Some(Layer::Lower))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Get the merged inode for a normalized path.
+    fn merged_stat(&self, path: &str) -> Result<Inode, VfsError> {
+        match self.locate(path)? {
+            Some(Layer::Upper) => self.upper.stat(path),
+            Some(Layer::Lower) => self.lower.stat(path),
+            None => Err(VfsError::NotFound),
+        }
+    }
+
+    /// Copy owner and permissions from an inode onto an upper entry.
+    fn copy_metadata(&self, path: &str, inode: &Inode) -> Result<(), VfsError> {
+        self.upper.chmod(path, inode.permissions.clone())?;
+        self.upper.chown(path, inode.owner_id)
+    }
+
+    /// Ensure directory `dir` and its ancestors exist in the upper layer.
+    fn copy_up_dirs(&self, dir: &str) -> Result<(), VfsError> {
+        if dir == "/" || self.upper.exists(dir)? {
+            return Ok(());
+        }
+        self.copy_up_dirs(&parent_path(dir))?;
+
+        let inode = self.lower.stat(dir)?;
+        if !inode.is_directory() {
+            return Err(VfsError::NotADirectory);
+        }
+        self.upper.mkdir(dir)?;
+        self.copy_metadata(dir, &inode)
+    }
+
+    /// Copy a lower entry into the upper layer so it can be modified.
+    fn copy_up(&self, path: &str) -> Result<(), VfsError> {
+        if self.upper.exists(path)? {
+            return Ok(());
+        }
+        let inode = self.lower.stat(path)?;
+        self.copy_up_dirs(&parent_path(path))?;
+
+        match &inode.inode_type {
+            InodeType::File => {
+                if inode.encrypted {
+                    return Err(VfsError::NotSupported(String::from(
+                        "Copy-up of encrypted lower-layer file",
+                    )));
+                }
+                let content = self.lower.read_file(path)?;
+                self.upper.write_file(path, &content)?;
+            }
+            InodeType::Directory => self.upper.mkdir(path)?,
+            InodeType::SymLink { target } => self.upper.symlink(target, path)?,
+        }
+        self.copy_metadata(path, &inode)
+    }
+
+    /// Prepare the upper layer for creating a new entry at `path`.
+    ///
+    /// Verifies the parent is a directory in the merged view, copies it up,
+    /// and clears any whiteout. Returns true if a whiteout was removed.
+    fn prepare_create(&self, path: &str) -> Result<bool, VfsError> {
+        let parent = parent_path(path);
+        if !self.merged_stat(&parent)?.is_directory() {
+            return Err(VfsError::NotADirectory);
+        }
+        self.copy_up_dirs(&parent)?;
+
+        let whiteout = whiteout_path(path);
+        if self.upper.exists(&whiteout)? {
+            self.upper.unlink(&whiteout)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Remove an entry from the merged view.
+    ///
+    /// Deletes the upper copy (if any) and records a whiteout when the lower
+    /// layer still provides the path.
+    fn remove_entry(&self, path: &str) -> Result<(), VfsError> {
+        let in_lower = self.in_lower(path)?;
+
+        if self.upper.exists(path)? {
+            if self.upper.stat(path)?.is_directory() {
+                self.upper.rmdir_recursive(path)?;
+            } else {
+                self.upper.unlink(path)?;
+            }
+        }
+
+        if in_lower {
+            self.copy_up_dirs(&parent_path(path))?;
+            self.upper.write_file(&whiteout_path(path), &[])?;
+        }
+
+        Ok(())
+    }
+
+    /// Accumulate usage for an inode and, for directories, its merged subtree.
+    fn accumulate_usage(&self, inode: &Inode, usage: &mut StorageUsage) -> Result<(), VfsError> {
+        match &inode.inode_type {
+            InodeType::File => usage.add_file(inode.size, inode.encrypted),
+            InodeType::Directory => {
+                usage.add_directory();
+                for entry in self.readdir(&inode.path)? {
+                    let child = self.merged_stat(&entry.path)?;
+                    self.accumulate_usage(&child, usage)?;
+                }
+            }
+            InodeType::SymLink { .. } => {
+                // Symlinks don't count toward storage
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<L: VfsService, U: VfsService> VfsService for OverlayVfs<L, U> {
+    fn mkdir(&self, path: &str) -> Result<(), VfsError> {
+        let path = overlay_path(path)?;
+
+        if self.locate(&path)?.is_some() {
+            return Err(VfsError::AlreadyExists);
+        }
+
+        let replaced_whiteout = self.prepare_create(&path)?;
+        self.upper.mkdir(&path)?;
+
+        // A directory recreated over a deleted lower one must not resurrect
+        // the lower contents.
+        if replaced_whiteout {
+            self.upper.write_file(&opaque_path(&path), &[])?;
+        }
+
+        Ok(())
+    }
+
+    fn mkdir_p(&self, path: &str) -> Result<(), VfsError> {
+        let path = overlay_path(path)?;
+
+        let mut current = String::from("/");
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            current = join_path(&current, component);
+            match self.locate(&current)? {
+                Some(_) => {
+                    if !self.merged_stat(&current)?.is_directory() {
+                        return Err(VfsError::NotADirectory);
+                    }
+                }
+                None => self.mkdir(&current)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn rmdir(&self, path: &str) -> Result<(), VfsError> {
+        let path = overlay_path(path)?;
+
+        if path == "/" {
+            return Err(VfsError::PermissionDenied);
+        }
+
+        if !self.merged_stat(&path)?.is_directory() {
+            return Err(VfsError::NotADirectory);
+        }
+
+        if !self.readdir(&path)?.is_empty() {
+            return Err(VfsError::DirectoryNotEmpty);
+        }
+
+        self.remove_entry(&path)
+    }
+
+    fn rmdir_recursive(&self, path: &str) -> Result<(), VfsError> {
+        let path = overlay_path(path)?;
+
+        if path == "/" {
+            return Err(VfsError::PermissionDenied);
+        }
+
+        if self.locate(&path)?.is_none() {
+            return Err(VfsError::NotFound);
+        }
+
+        self.remove_entry(&path)
+    }
+
+    fn readdir(&self, path: &str) -> Result<Vec<DirEntry>, VfsError> {
+        let path = overlay_path(path)?;
+
+        if !self.merged_stat(&path)?.is_directory() {
+            return Err(VfsError::NotADirectory);
+        }
+
+        let mut entries: BTreeMap<String, DirEntry> = BTreeMap::new();
+        let mut opaque = false;
+
+        if self.upper.exists(&path)? {
+            opaque = self.upper.exists(&opaque_path(&path))?;
+            for entry in self.upper.readdir(&path)? {
+                if !is_whiteout_name(&entry.name) {
+                    entries.insert(entry.name.clone(), entry);
+                }
+            }
+        }
+
+        if !opaque && self.in_lower(&path)? && self.lower.stat(&path)?.is_directory() {
+            for entry in self.lower.readdir(&path)? {
+                if entries.contains_key(&entry.name)
+                    || self.upper.exists(&whiteout_path(&entry.path))?
+                {
+                    continue;
+                }
+                entries.insert(entry.name.clone(), entry);
+            }
+        }
+
+        Ok(entries.into_values().collect())
+    }
+
+    fn write_file(&self, path: &str, content: &[u8]) -> Result<(), VfsError> {
+        let path = overlay_path(path)?;
+
+        let shadowed = match self.locate(&path)? {
+            Some(layer) => {
+                let inode = self.merged_stat(&path)?;
+                if inode.is_directory() {
+                    return Err(VfsError::NotAFile);
+                }
+                (layer == Layer::Lower).then_some(inode)
+            }
+            None => None,
+        };
+
+        self.prepare_create(&path)?;
+        self.upper.write_file(&path, content)?;
+
+        // Overwriting a lower file keeps its ownership and permissions
+        if let Some(inode) = shadowed {
+            self.copy_metadata(&path, &inode)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_file_encrypted(
+        &self,
+        path: &str,
+        content: &[u8],
+        key: &[u8; 32],
+    ) -> Result<(), VfsError> {
+        let path = overlay_path(path)?;
+
+        let shadowed = match self.locate(&path)? {
+            Some(layer) => {
+                let inode = self.merged_stat(&path)?;
+                if inode.is_directory() {
+                    return Err(VfsError::NotAFile);
+                }
+                (layer == Layer::Lower).then_some(inode)
+            }
+            None => None,
+        };
+
+        self.prepare_create(&path)?;
+        self.upper.write_file_encrypted(&path, content, key)?;
+
+        if let Some(inode) = shadowed {
+            self.copy_metadata(&path, &inode)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_file(&self, path: &str) -> Result<Vec<u8>, VfsError> {
+        let path = overlay_path(path)?;
+
+        match self.locate(&path)? {
+            Some(Layer::Upper) => self.upper.read_file(&path),
+            Some(Layer::Lower) => self.lower.read_file(&path),
+            None => Err(VfsError::NotFound),
+        }
+    }
+
+    fn read_file_encrypted(&self, path: &str, key: &[u8; 32]) -> Result<Vec<u8>, VfsError> {
+        let path = overlay_path(path)?;
+
+        match self.locate(&path)? {
+            Some(Layer::Upper) => self.upper.read_file_encrypted(&path, key),
+            Some(Layer::Lower) => self.lower.read_file_encrypted(&path, key),
+            None => Err(VfsError::NotFound),
+        }
+    }
+
+    fn unlink(&self, path: &str) -> Result<(), VfsError> {
+        let path = overlay_path(path)?;
+
+        let inode = self.merged_stat(&path)?;
+        if !inode.is_file() && !inode.is_symlink() {
+            return Err(VfsError::NotAFile);
+        }
+
+        self.remove_entry(&path)
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), VfsError> {
+        let from = overlay_path(from)?;
+        let to = overlay_path(to)?;
+
+        let inode = self.merged_stat(&from)?;
+
+        match &inode.inode_type {
+            InodeType::File => {
+                let content = self.read_file(&from)?;
+                self.write_file(&to, &content)?;
+            }
+            InodeType::SymLink { target } => self.symlink(target, &to)?,
+            InodeType::Directory => {
+                // Directories that exist only in the upper layer can move as a
+                // whole; merged directories would need their lower contents
+                // copied up first.
+                if self.in_lower(&from)? {
+                    return Err(VfsError::NotSupported(String::from(
+                        "Rename of a directory backed by the lower layer",
+                    )));
+                }
+                self.prepare_create(&to)?;
+                return self.upper.rename(&from, &to);
+            }
+        }
+
+        self.copy_metadata(&to, &inode)?;
+        self.unlink(&from)
+    }
+
+    fn copy(&self, from: &str, to: &str) -> Result<(), VfsError> {
+        let from = overlay_path(from)?;
+        let to = overlay_path(to)?;
+
+        if !self.merged_stat(&from)?.is_file() {
+            return Err(VfsError::NotAFile);
+        }
+
+        let content = self.read_file(&from)?;
+        self.write_file(&to, &content)
+    }
+
+    fn stat(&self, path: &str) -> Result<Inode, VfsError> {
+        let path = overlay_path(path)?;
+        self.merged_stat(&path)
+    }
+
+    fn exists(&self, path: &str) -> Result<bool, VfsError> {
+        let path = overlay_path(path)?;
+        Ok(self.locate(&path)?.is_some())
+    }
+
+    fn chmod(&self, path: &str, perms: FilePermissions) -> Result<(), VfsError> {
+        let path = overlay_path(path)?;
+
+        match self.locate(&path)? {
+            Some(Layer::Upper) => {}
+            Some(Layer::Lower) => self.copy_up(&path)?,
+            None => return Err(VfsError::NotFound),
+        }
+
+        self.upper.chmod(&path, perms)
+    }
+
+    fn chown(&self, path: &str, owner_id: Option<UserId>) -> Result<(), VfsError> {
+        let path = overlay_path(path)?;
+
+        match self.locate(&path)? {
+            Some(Layer::Upper) => {}
+            Some(Layer::Lower) => self.copy_up(&path)?,
+            None => return Err(VfsError::NotFound),
+        }
+
+        self.upper.chown(&path, owner_id)
+    }
+
+    fn symlink(&self, target: &str, link_path: &str) -> Result<(), VfsError> {
+        let link_path = overlay_path(link_path)?;
+
+        if self.locate(&link_path)?.is_some() {
+            return Err(VfsError::AlreadyExists);
+        }
+
+        self.prepare_create(&link_path)?;
+        self.upper.symlink(target, &link_path)
+    }
+
+    fn readlink(&self, path: &str) -> Result<String, VfsError> {
+        let path = overlay_path(path)?;
+
+        match self.locate(&path)? {
+            Some(Layer::Upper) => self.upper.readlink(&path),
+            Some(Layer::Lower) => self.lower.readlink(&path),
+            None => Err(VfsError::NotFound),
+        }
+    }
+
+    fn resolve_path(&self, path: &str) -> Result<String, VfsError> {
+        let path = overlay_path(path)?;
+
+        if self.locate(&path)?.is_some() {
+            Ok(path)
+        } else {
+            Err(VfsError::NotFound)
+        }
+    }
+
+    fn get_usage(&self, path: &str) -> Result<StorageUsage, VfsError> {
+        let path = overlay_path(path)?;
+
+        let inode = self.merged_stat(&path)?;
+        let mut usage = StorageUsage::new();
+        self.accumulate_usage(&inode, &mut usage)?;
+
+        Ok(usage)
+    }
+
+    fn get_quota(&self, user_id: UserId) -> Result<StorageQuota, VfsError> {
+        // Quotas only apply to data the user can write
+        self.upper.get_quota(user_id)
+    }
+
+    fn set_quota(&self, user_id: UserId, max_bytes: u64) -> Result<(), VfsError> {
+        self.upper.set_quota(user_id, max_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MemoryVfs;
+
+    /// Lower layer with a small read-only system tree.
+    fn overlay() -> OverlayVfs<MemoryVfs, MemoryVfs> {
+        let lower = MemoryVfs::new();
+        lower.mkdir_p("/system/config").unwrap();
+        lower
+            .chmod("/system", FilePermissions::system_only())
+            .unwrap();
+        lower
+            .write_file("/system/config/machine.json", b"{}")
+            .unwrap();
+        lower.write_file("/system/version", b"1").unwrap();
+        OverlayVfs::new(lower, MemoryVfs::new())
+    }
+
+    #[test]
+    fn test_reads_fall_through_to_lower() {
+        let vfs = overlay();
+
+        assert!(vfs.exists("/system/config").unwrap());
+        assert_eq!(vfs.read_file("/system/version").unwrap(), b"1");
+        assert!(vfs.stat("/system").unwrap().is_directory());
+        assert!(!vfs.upper().exists("/system").unwrap());
+    }
+
+    #[test]
+    fn test_writes_land_in_upper() {
+        let vfs = overlay();
+
+        vfs.write_file("/system/version", b"2").unwrap();
+
+        assert_eq!(vfs.read_file("/system/version").unwrap(), b"2");
+        assert_eq!(vfs.lower().read_file("/system/version").unwrap(), b"1");
+
+        // Parent directory was copied up with its lower permissions
+        let dir = vfs.upper().stat("/system").unwrap();
+        assert_eq!(dir.permissions, FilePermissions::system_only());
+    }
+
+    #[test]
+    fn test_unlink_lower_file_creates_whiteout() {
+        let vfs = overlay();
+
+        vfs.unlink("/system/version").unwrap();
+
+        assert!(!vfs.exists("/system/version").unwrap());
+        assert!(vfs.read_file("/system/version").is_err());
+        assert!(vfs.lower().exists("/system/version").unwrap());
+        assert!(vfs.upper().exists("/system/.wh.version").unwrap());
+
+        let names: Vec<String> = vfs
+            .readdir("/system")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, ["config"]);
+
+        // Recreating the file clears the whiteout
+        vfs.write_file("/system/version", b"3").unwrap();
+        assert_eq!(vfs.read_file("/system/version").unwrap(), b"3");
+        assert!(!vfs.upper().exists("/system/.wh.version").unwrap());
+    }
+
+    #[test]
+    fn test_recreated_directory_is_opaque() {
+        let vfs = overlay();
+
+        vfs.rmdir_recursive("/system/config").unwrap();
+        assert!(!vfs.exists("/system/config/machine.json").unwrap());
+
+        vfs.mkdir("/system/config").unwrap();
+        assert!(vfs.readdir("/system/config").unwrap().is_empty());
+        assert!(!vfs.exists("/system/config/machine.json").unwrap());
+    }
+
+    #[test]
+    fn test_readdir_merges_layers() {
+        let vfs = overlay();
+
+        vfs.write_file("/system/local.txt", b"upper").unwrap();
+        vfs.write_file("/system/version", b"2").unwrap();
+
+        let entries = vfs.readdir("/system").unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["config", "local.txt", "version"]);
+
+        let version = entries.iter().find(|e| e.name == "version").unwrap();
+        assert_eq!(version.size, 1);
+    }
+
+    #[test]
+    fn test_rmdir_requires_merged_empty() {
+        let vfs = overlay();
+
+        assert!(matches!(
+            vfs.rmdir("/system/config"),
+            Err(VfsError::DirectoryNotEmpty)
+        ));
+
+        vfs.unlink("/system/config/machine.json").unwrap();
+        vfs.rmdir("/system/config").unwrap();
+        assert!(!vfs.exists("/system/config").unwrap());
+    }
+
+    #[test]
+    fn test_chmod_copies_up_lower_file() {
+        let vfs = overlay();
+
+        vfs.chmod("/system/version", FilePermissions::world_readable())
+            .unwrap();
+
+        assert_eq!(vfs.upper().read_file("/system/version").unwrap(), b"1");
+        assert!(vfs.stat("/system/version").unwrap().permissions.world_read);
+        assert!(
+            !vfs.lower()
+                .stat("/system/version")
+                .unwrap()
+                .permissions
+                .world_read
+        );
+    }
+
+    #[test]
+    fn test_rename_lower_file() {
+        let vfs = overlay();
+
+        vfs.rename("/system/version", "/system/version.old")
+            .unwrap();
+
+        assert!(!vfs.exists("/system/version").unwrap());
+        assert_eq!(vfs.read_file("/system/version.old").unwrap(), b"1");
+        assert!(vfs.lower().exists("/system/version").unwrap());
+
+        assert!(matches!(
+            vfs.rename("/system/config", "/system/settings"),
+            Err(VfsError::NotSupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_whiteout_names_rejected() {
+        let vfs = overlay();
+
+        assert!(matches!(
+            vfs.write_file("/system/.wh.version", b""),
+            Err(VfsError::InvalidPath(_))
+        ));
+        assert!(vfs.stat("/system/.wh..wh..opq").is_err());
+    }
+
+    #[test]
+    fn test_usage_counts_merged_tree() {
+        let vfs = overlay();
+
+        vfs.unlink("/system/version").unwrap();
+        vfs.write_file("/system/notes.txt", b"12345").unwrap();
+
+        let usage = vfs.get_usage("/system").unwrap();
+        assert_eq!(usage.file_count, 2); // machine.json + notes.txt
+        assert_eq!(usage.used_bytes, 7);
+        assert_eq!(usage.directory_count, 2);
+    }
+}
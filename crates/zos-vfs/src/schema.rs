@@ -0,0 +1,423 @@
+//! On-disk schema versioning for VFS metadata.
+//!
+//! Every persisted inode carries a `schema_version`. Records written before
+//! versioning existed have no such field and are treated as version 0.
+//!
+//! Upgrades go through a per-record [`Schema`]: an ordered list of
+//! [`Migration`]s, each lifting the raw JSON of a record by exactly one
+//! version. Decoding a record runs every migration between its stored
+//! version and the current one before deserializing, so callers only ever
+//! see the current layout.
+//!
+//! Two paths keep storage moving forward:
+//!
+//! - **Lazy**: [`decode_inode`] upgrades in memory on every read
+//! - **Eager**: the VFS service sweeps the tree in the background and writes
+//!   upgraded records back (`Upgraded::migrated_from` tells it which records
+//!   need rewriting)
+//!
+//! Records from a *newer* schema than this build understands are rejected
+//! rather than downgraded, so an older build never overwrites data it
+//! cannot represent.
+//!
+//! File content is stored as raw bytes without a record envelope and is not
+//! versioned here.
+//!
+//! # Adding a version
+//!
+//! 1. Bump [`INODE_SCHEMA_VERSION`]
+//! 2. Append a migration with `from` set to the previous version
+//! 3. Add a fixture of the previous layout under `fixtures/` and a test that
+//!    decodes it
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::core::{FilePermissions, Inode};
+
+/// Current inode schema version.
+pub const INODE_SCHEMA_VERSION: u32 = 2;
+
+/// JSON field holding a record's schema version.
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// Upgrade a record's JSON object by one version.
+pub type MigrateFn = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// A single-step upgrade from `from` to `from + 1`.
+pub struct Migration {
+    /// Version this migration upgrades from
+    pub from: u32,
+    /// What changed, for logs
+    pub description: &'static str,
+    /// The upgrade itself
+    pub migrate: MigrateFn,
+}
+
+/// Migration registry for one kind of persisted record.
+pub struct Schema {
+    /// Record kind, for errors and logs
+    pub name: &'static str,
+    /// Version written by this build
+    pub current: u32,
+    /// Migrations ordered by `from`, one per version below `current`
+    pub migrations: &'static [Migration],
+}
+
+/// Errors from decoding a versioned record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaError {
+    /// Not a JSON object, or unparseable after migration
+    Malformed(String),
+    /// Written by a newer build
+    TooNew { found: u32, supported: u32 },
+    /// No migration registered from this version
+    MissingMigration { from: u32 },
+    /// A migration rejected the record
+    MigrationFailed { from: u32, reason: String },
+}
+
+impl SchemaError {
+    /// Human-readable error message.
+    pub fn message(&self) -> String {
+        match self {
+            SchemaError::Malformed(e) => format!("Malformed record: {}", e),
+            SchemaError::TooNew { found, supported } => format!(
+                "Record schema v{} is newer than supported v{}",
+                found, supported
+            ),
+            SchemaError::MissingMigration { from } => {
+                format!("No migration from schema v{}", from)
+            }
+            SchemaError::MigrationFailed { from, reason } => {
+                format!("Migration from schema v{} failed: {}", from, reason)
+            }
+        }
+    }
+}
+
+/// A decoded record and the version it was stored as.
+#[derive(Clone, Debug)]
+pub struct Upgraded<T> {
+    /// The record in the current layout
+    pub record: T,
+    /// Stored version if the record was migrated, `None` if already current
+    pub migrated_from: Option<u32>,
+}
+
+impl Schema {
+    /// Schema version stored in a record's JSON object (0 if absent).
+    pub fn version_of(object: &Map<String, Value>) -> Result<u32, SchemaError> {
+        match object.get(SCHEMA_VERSION_FIELD) {
+            None | Some(Value::Null) => Ok(0),
+            Some(v) => v
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| SchemaError::Malformed(String::from("invalid schema_version"))),
+        }
+    }
+
+    /// Upgrade a record's JSON object in place to the current version.
+    ///
+    /// Returns the version it was stored as if any migration ran.
+    pub fn upgrade(&self, object: &mut Map<String, Value>) -> Result<Option<u32>, SchemaError> {
+        let stored = Self::version_of(object)?;
+        if stored > self.current {
+            return Err(SchemaError::TooNew {
+                found: stored,
+                supported: self.current,
+            });
+        }
+
+        let mut version = stored;
+        while version < self.current {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|m| m.from == version)
+                .ok_or(SchemaError::MissingMigration { from: version })?;
+            (migration.migrate)(object).map_err(|reason| SchemaError::MigrationFailed {
+                from: version,
+                reason,
+            })?;
+            version += 1;
+            object.insert(String::from(SCHEMA_VERSION_FIELD), Value::from(version));
+        }
+
+        Ok((stored != self.current).then_some(stored))
+    }
+
+    /// Decode a stored record, migrating it to the current layout.
+    pub fn decode<T: DeserializeOwned>(&self, data: &[u8]) -> Result<Upgraded<T>, SchemaError> {
+        let value: Value =
+            serde_json::from_slice(data).map_err(|e| SchemaError::Malformed(e.to_string()))?;
+        let Value::Object(mut object) = value else {
+            return Err(SchemaError::Malformed(format!(
+                "{} record is not a JSON object",
+                self.name
+            )));
+        };
+
+        let migrated_from = self.upgrade(&mut object)?;
+        let record = serde_json::from_value(Value::Object(object))
+            .map_err(|e| SchemaError::Malformed(e.to_string()))?;
+        Ok(Upgraded {
+            record,
+            migrated_from,
+        })
+    }
+}
+
+/// Inode record schema.
+pub const INODE_SCHEMA: Schema = Schema {
+    name: "inode",
+    current: INODE_SCHEMA_VERSION,
+    migrations: &[
+        Migration {
+            from: 0,
+            description: "unversioned inode to v1",
+            migrate: migrate_inode_v0,
+        },
+        Migration {
+            from: 1,
+            description: "extended attributes",
+            migrate: migrate_inode_v1,
+        },
+    ],
+};
+
+/// Decode a stored inode, upgrading older schema versions.
+pub fn decode_inode(data: &[u8]) -> Result<Upgraded<Inode>, SchemaError> {
+    INODE_SCHEMA.decode(data)
+}
+
+/// v0 -> v1.
+///
+/// Unversioned inodes come from two writers: the Rust `Inode` type, and the
+/// original JS storage layer, whose records use lowercase `inode_type`
+/// strings, keep symlink targets in a separate `symlink_target` field, store
+/// `content_hash` as hex and may lack `permissions` and `accessed_at`.
+/// Numeric `owner_id`s are rewritten in the hex string form `Inode` expects.
+fn migrate_inode_v0(object: &mut Map<String, Value>) -> Result<(), String> {
+    let symlink_target = object.remove("symlink_target");
+
+    let inode_type = match object.get("inode_type") {
+        Some(Value::String(s)) => match s.as_str() {
+            "file" | "File" => Value::from("File"),
+            "directory" | "Directory" => Value::from("Directory"),
+            "symlink" => {
+                let target = symlink_target
+                    .as_ref()
+                    .and_then(Value::as_str)
+                    .ok_or("symlink without target")?;
+                serde_json::json!({ "SymLink": { "target": target } })
+            }
+            other => return Err(format!("unknown inode_type {:?}", other)),
+        },
+        Some(other @ Value::Object(_)) => other.clone(),
+        _ => return Err(String::from("missing inode_type")),
+    };
+    let is_directory = inode_type == "Directory";
+    object.insert(String::from("inode_type"), inode_type);
+
+    if let Some(Value::Number(n)) = object.get("owner_id") {
+        let id = n.as_u64().ok_or("owner_id is not an unsigned integer")?;
+        object.insert(
+            String::from("owner_id"),
+            Value::from(format!("{:032x}", id)),
+        );
+    }
+
+    if !object.contains_key("permissions") {
+        let permissions = if is_directory {
+            FilePermissions::user_dir_default()
+        } else {
+            FilePermissions::user_default()
+        };
+        let permissions = serde_json::to_value(permissions).map_err(|e| e.to_string())?;
+        object.insert(String::from("permissions"), permissions);
+    }
+
+    let modified_at = object.get("modified_at").cloned().unwrap_or(Value::from(0));
+    object.entry("accessed_at").or_insert(modified_at);
+    object.entry("size").or_insert(Value::from(0));
+    object.entry("encrypted").or_insert(Value::Bool(false));
+
+    let content_hash = match object.get("content_hash") {
+        Some(Value::String(hex)) => decode_hash_hex(hex).map_or(Value::Null, |bytes| {
+            Value::Array(bytes.iter().map(|b| Value::from(*b)).collect())
+        }),
+        Some(other) => other.clone(),
+        None => Value::Null,
+    };
+    object.insert(String::from("content_hash"), content_hash);
+
+    Ok(())
+}
+
+/// v1 -> v2.
+///
+/// v2 adds `xattrs`, which a record without any leaves out, so v1 records
+/// need no change. The version still moves so that a v1 build, which would
+/// drop the attributes on its next write, rejects v2 records instead.
+fn migrate_inode_v1(_object: &mut Map<String, Value>) -> Result<(), String> {
+    Ok(())
+}
+
+/// Parse a 64-character hex SHA-256 digest.
+fn decode_hash_hex(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 {
+        return None;
+    }
+    let bytes: Vec<u8> = (0..32)
+        .map(|i| u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok())
+        .collect::<Option<_>>()?;
+    bytes.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::InodeType;
+
+    const V0_JS_FILE: &[u8] = include_bytes!("../fixtures/inode_v0_js_file.json");
+    const V0_JS_SYMLINK: &[u8] = include_bytes!("../fixtures/inode_v0_js_symlink.json");
+    const V0_RUST_DIR: &[u8] = include_bytes!("../fixtures/inode_v0_rust_dir.json");
+    const V1_FILE: &[u8] = include_bytes!("../fixtures/inode_v1_file.json");
+    const V2_FILE: &[u8] = include_bytes!("../fixtures/inode_v2_file.json");
+
+    #[test]
+    fn test_migrations_cover_every_version() {
+        for version in 0..INODE_SCHEMA.current {
+            let count = INODE_SCHEMA
+                .migrations
+                .iter()
+                .filter(|m| m.from == version)
+                .count();
+            assert_eq!(count, 1, "expected one migration from v{}", version);
+        }
+        assert!(INODE_SCHEMA
+            .migrations
+            .iter()
+            .all(|m| m.from < INODE_SCHEMA.current));
+    }
+
+    #[test]
+    fn test_new_inodes_are_current() {
+        let inode = Inode::new_file(
+            String::from("/a"),
+            String::from("/"),
+            String::from("a"),
+            None,
+            0,
+            None,
+            0,
+        );
+        assert_eq!(inode.schema_version, INODE_SCHEMA_VERSION);
+
+        let decoded = decode_inode(&serde_json::to_vec(&inode).unwrap()).unwrap();
+        assert_eq!(decoded.migrated_from, None);
+    }
+
+    #[test]
+    fn test_v0_js_file_fixture() {
+        let decoded = decode_inode(V0_JS_FILE).unwrap();
+        assert_eq!(decoded.migrated_from, Some(0));
+
+        let inode = decoded.record;
+        assert_eq!(inode.schema_version, INODE_SCHEMA_VERSION);
+        assert_eq!(inode.path, "/home/1/notes.txt");
+        assert_eq!(inode.inode_type, InodeType::File);
+        assert_eq!(inode.owner_id, Some(1));
+        assert_eq!(inode.permissions, FilePermissions::user_default());
+        assert_eq!(inode.accessed_at, inode.modified_at);
+        assert_eq!(inode.size, 11);
+        let hash = inode.content_hash.unwrap();
+        assert_eq!(hash[0], 0xb9);
+        assert_eq!(hash[31], 0xe9);
+    }
+
+    #[test]
+    fn test_v0_js_symlink_fixture() {
+        let inode = decode_inode(V0_JS_SYMLINK).unwrap().record;
+        assert_eq!(
+            inode.inode_type,
+            InodeType::SymLink {
+                target: String::from("/home/1/notes.txt")
+            }
+        );
+        assert!(inode.content_hash.is_none());
+    }
+
+    #[test]
+    fn test_v0_rust_dir_fixture() {
+        let decoded = decode_inode(V0_RUST_DIR).unwrap();
+        assert_eq!(decoded.migrated_from, Some(0));
+        let inode = decoded.record;
+        assert!(inode.is_directory());
+        // Explicit permissions from the old record are kept
+        assert!(inode.permissions.world_read);
+        assert_eq!(inode.owner_id, None);
+    }
+
+    #[test]
+    fn test_v1_file_fixture() {
+        let decoded = decode_inode(V1_FILE).unwrap();
+        assert_eq!(decoded.migrated_from, Some(1));
+        assert_eq!(decoded.record.schema_version, INODE_SCHEMA_VERSION);
+        assert_eq!(decoded.record.path, "/home/1/report.md");
+        assert!(decoded.record.xattrs.is_empty());
+    }
+
+    #[test]
+    fn test_v2_fixture_decodes_without_migration() {
+        let decoded = decode_inode(V2_FILE).unwrap();
+        assert_eq!(decoded.migrated_from, None);
+        assert_eq!(decoded.record.path, "/home/1/photo.png");
+        assert_eq!(decoded.record.xattrs.get("mime_type").unwrap(), "image/png");
+        assert_eq!(
+            decoded.record.xattrs.get("origin_url").unwrap(),
+            "https://example.com/photo.png"
+        );
+    }
+
+    #[test]
+    fn test_migrated_record_round_trips_as_current() {
+        let inode = decode_inode(V0_JS_FILE).unwrap().record;
+        let rewritten = serde_json::to_vec(&inode).unwrap();
+        let decoded = decode_inode(&rewritten).unwrap();
+        assert_eq!(decoded.migrated_from, None);
+        assert_eq!(decoded.record.content_hash, inode.content_hash);
+    }
+
+    #[test]
+    fn test_newer_schema_is_rejected() {
+        let data = br#"{"schema_version": 99, "path": "/x"}"#;
+        assert_eq!(
+            decode_inode(data).unwrap_err(),
+            SchemaError::TooNew {
+                found: 99,
+                supported: INODE_SCHEMA_VERSION
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_records() {
+        assert!(matches!(
+            decode_inode(b"[1, 2]"),
+            Err(SchemaError::Malformed(_))
+        ));
+        assert!(matches!(
+            decode_inode(br#"{"path": "/x", "inode_type": "fifo"}"#),
+            Err(SchemaError::MigrationFailed { from: 0, .. })
+        ));
+        assert!(matches!(
+            decode_inode(br#"{"schema_version": "one"}"#),
+            Err(SchemaError::Malformed(_))
+        ));
+    }
+}
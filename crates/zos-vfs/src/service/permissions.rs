@@ -5,6 +5,8 @@ use crate::core::{Inode, UserId};
 /// Process classification for permission checking.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ProcessClass {
+    /// Init: passes every permission check
+    Superuser,
     /// System processes (init, terminal, etc.)
     System,
     /// Runtime services (storage, network, identity, etc.)
@@ -42,6 +44,10 @@ impl PermissionContext {
 
 /// Check if a context has read permission on an inode.
 pub fn check_read(inode: &Inode, ctx: &PermissionContext) -> bool {
+    if ctx.process_class == ProcessClass::Superuser {
+        return true;
+    }
+
     // System processes check system_read
     if ctx.process_class == ProcessClass::System || ctx.process_class == ProcessClass::Runtime {
         return inode.permissions.system_read;
@@ -60,6 +66,10 @@ pub fn check_read(inode: &Inode, ctx: &PermissionContext) -> bool {
 
 /// Check if a context has write permission on an inode.
 pub fn check_write(inode: &Inode, ctx: &PermissionContext) -> bool {
+    if ctx.process_class == ProcessClass::Superuser {
+        return true;
+    }
+
     // System processes (like IdentityService) can write to user directories
     // This allows system services to manage user data in paths like ~/.zos/identity/
     if ctx.process_class == ProcessClass::System || ctx.process_class == ProcessClass::Runtime {
@@ -93,7 +103,7 @@ pub fn check_execute(inode: &Inode, ctx: &PermissionContext) -> bool {
     }
 
     // System processes always have traverse
-    if ctx.process_class == ProcessClass::System || ctx.process_class == ProcessClass::Runtime {
+    if ctx.process_class != ProcessClass::Application {
         return true;
     }
 
@@ -235,4 +245,34 @@ mod tests {
         inode.permissions.system_write = true;
         assert!(check_write(&inode, &system_ctx));
     }
+
+    #[test]
+    fn test_superuser_bypasses_permissions() {
+        let mut inode = Inode::new_file(
+            String::from("/home/1/secret"),
+            String::from("/home/1"),
+            String::from("secret"),
+            Some(1),
+            100,
+            None,
+            1000,
+        );
+        inode.permissions = FilePermissions {
+            owner_read: false,
+            owner_write: false,
+            owner_execute: false,
+            system_read: false,
+            system_write: false,
+            world_read: false,
+            world_write: false,
+        };
+        let superuser = PermissionContext {
+            user_id: None,
+            process_class: ProcessClass::Superuser,
+        };
+        assert!(check_read(&inode, &superuser));
+        assert!(check_write(&inode, &superuser));
+        assert!(!check_read(&inode, &PermissionContext::system()));
+        assert!(!check_read(&inode, &PermissionContext::user(1)));
+    }
 }
@@ -0,0 +1,292 @@
+//! Snapshot layout.
+//!
+//! A snapshot is a copy of the inodes of a directory tree as they were at
+//! one point in time, with a reference to every file's content. Content is
+//! shared the way hard links share it (see [`crate::storage::blobs`]):
+//! taking the snapshot turns each file's content record into a link to a
+//! blob, and the snapshot holds one count of every blob it names. A later
+//! write gives the live file a record of its own, so the snapshot keeps the
+//! content it saw without anything being copied.
+//!
+//! # Records
+//!
+//! ```text
+//! snapshots:{user}       index: JSON [SnapshotInfo], oldest first
+//! snapshot:{user}:{id}   manifest: JSON SnapshotManifest
+//! ```
+//!
+//! A snapshot belongs to the user whose home directory its root is in. The
+//! trash directory is left out of snapshots and left alone by restores.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{extract_user_id, Inode, UserId, VfsError};
+use crate::ipc::SnapshotInfo;
+use crate::storage::blobs::BlobHash;
+use crate::trash::{is_trashed, trash_dir};
+
+/// Most snapshots one user can keep.
+pub const MAX_SNAPSHOTS_PER_USER: usize = 16;
+
+/// Most entries (files and directories) one snapshot can hold.
+pub const MAX_SNAPSHOT_ENTRIES: usize = 10_000;
+
+/// Longest snapshot label, in bytes.
+pub const MAX_SNAPSHOT_LABEL_LEN: usize = 255;
+
+/// Format version written by [`SnapshotManifest::encode`].
+pub const SNAPSHOT_MANIFEST_VERSION: u8 = 1;
+
+/// Storage key of `user_id`'s snapshot index.
+pub fn snapshot_index_key(user_id: UserId) -> String {
+    format!("snapshots:{}", user_id)
+}
+
+/// Storage key of the manifest of snapshot `id` of `user_id`.
+pub fn snapshot_key(user_id: UserId, id: &str) -> String {
+    format!("snapshot:{}:{}", user_id, id)
+}
+
+/// ID for a snapshot taken at `created_at`.
+///
+/// `seq` tells apart snapshots taken in the same millisecond.
+pub fn snapshot_id(created_at: u64, seq: u32) -> String {
+    format!("{}-{}", created_at, seq)
+}
+
+/// Check that `id` could have been made by [`snapshot_id`].
+pub fn validate_snapshot_id(id: &str) -> Result<(), VfsError> {
+    if id.is_empty() || id.len() > 64 || !id.bytes().all(|b| b.is_ascii_digit() || b == b'-') {
+        return Err(VfsError::InvalidRequest(format!(
+            "Invalid snapshot ID: {:?}",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// Check a label given at creation.
+pub fn validate_label(label: &str) -> Result<(), VfsError> {
+    if label.len() > MAX_SNAPSHOT_LABEL_LEN || label.chars().any(char::is_control) {
+        return Err(VfsError::InvalidRequest(String::from(
+            "Snapshot label too long or has control characters",
+        )));
+    }
+    Ok(())
+}
+
+/// The user a snapshot of `root` belongs to.
+pub fn root_user(root: &str) -> Result<UserId, VfsError> {
+    extract_user_id(root).ok_or_else(|| {
+        VfsError::InvalidRequest(String::from(
+            "Only home directories and directories under them can be snapshotted",
+        ))
+    })
+}
+
+/// Whether `path` is left out of snapshots: the trash directory and
+/// everything in it.
+pub fn is_excluded(path: &str) -> bool {
+    is_trashed(path) || extract_user_id(path).is_some_and(|user_id| path == trash_dir(user_id))
+}
+
+/// Parse a user's snapshot index.
+pub fn decode_index(record: &[u8]) -> Result<Vec<SnapshotInfo>, VfsError> {
+    serde_json::from_slice(record)
+        .map_err(|e| VfsError::StorageError(format!("Snapshot index corrupt: {}", e)))
+}
+
+/// One file or directory in a snapshot.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// The inode as it was
+    pub inode: Inode,
+    /// Blob holding a file's content record (files only)
+    #[serde(default)]
+    pub content: Option<BlobHash>,
+}
+
+/// Everything a snapshot holds.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Format version
+    pub version: u8,
+    /// What the index says about it
+    pub info: SnapshotInfo,
+    /// Entries, sorted by path once finished, so parents precede children
+    pub entries: Vec<SnapshotEntry>,
+}
+
+impl SnapshotManifest {
+    /// An empty manifest for a snapshot of `root`.
+    pub fn new(id: String, root: String, label: Option<String>, created_at: u64) -> Self {
+        Self {
+            version: SNAPSHOT_MANIFEST_VERSION,
+            info: SnapshotInfo {
+                id,
+                root,
+                label,
+                created_at,
+                files: 0,
+                size: 0,
+            },
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add an entry, refusing the snapshot once it is too large.
+    pub fn push(&mut self, entry: SnapshotEntry) -> Result<(), VfsError> {
+        if self.entries.len() >= MAX_SNAPSHOT_ENTRIES {
+            return Err(VfsError::InvalidRequest(format!(
+                "Tree has more than {} entries",
+                MAX_SNAPSHOT_ENTRIES
+            )));
+        }
+        if entry.inode.is_file() {
+            self.info.files += 1;
+            self.info.size += entry.inode.size;
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Sort the entries so they can be looked up and restored top-down.
+    pub fn finish(&mut self) {
+        self.entries.sort_by(|a, b| a.inode.path.cmp(&b.inode.path));
+    }
+
+    /// The entry at `path`, in a finished manifest.
+    pub fn entry(&self, path: &str) -> Option<&SnapshotEntry> {
+        self.entries
+            .binary_search_by(|entry| entry.inode.path.as_str().cmp(path))
+            .ok()
+            .map(|i| &self.entries[i])
+    }
+
+    /// Every blob reference the snapshot holds.
+    pub fn blobs(&self) -> impl Iterator<Item = &BlobHash> {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry.content.as_ref())
+    }
+
+    /// Serialize for storage.
+    pub fn encode(&self) -> Result<Vec<u8>, VfsError> {
+        serde_json::to_vec(self)
+            .map_err(|e| VfsError::StorageError(format!("Failed to serialize snapshot: {}", e)))
+    }
+
+    /// Parse a record written by [`SnapshotManifest::encode`].
+    pub fn decode(record: &[u8]) -> Result<Self, VfsError> {
+        let manifest: Self = serde_json::from_slice(record)
+            .map_err(|e| VfsError::StorageError(format!("Snapshot corrupt: {}", e)))?;
+        if manifest.version != SNAPSHOT_MANIFEST_VERSION {
+            return Err(VfsError::StorageError(format!(
+                "Snapshot has unknown version {}",
+                manifest.version
+            )));
+        }
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn dir(path: &str) -> SnapshotEntry {
+        let name = path.rsplit('/').next().unwrap().to_string();
+        SnapshotEntry {
+            inode: Inode::new_directory(path.into(), crate::parent_path(path), name, Some(7), 1),
+            content: None,
+        }
+    }
+
+    fn file(path: &str, size: u64) -> SnapshotEntry {
+        let name = path.rsplit('/').next().unwrap().to_string();
+        SnapshotEntry {
+            inode: Inode::new_file(
+                path.into(),
+                crate::parent_path(path),
+                name,
+                Some(7),
+                size,
+                None,
+                1,
+            ),
+            content: Some([size as u8; 32]),
+        }
+    }
+
+    #[test]
+    fn test_keys_and_ids() {
+        assert_eq!(snapshot_index_key(7), "snapshots:7");
+        assert_eq!(snapshot_key(7, "100-0"), "snapshot:7:100-0");
+        assert_eq!(snapshot_id(100, 2), "100-2");
+        assert!(validate_snapshot_id("100-2").is_ok());
+        for bad in ["", "a", "1:2", "../1", "1/2"] {
+            assert!(validate_snapshot_id(bad).is_err(), "{:?}", bad);
+        }
+        assert!(validate_label("before update").is_ok());
+        assert!(validate_label("a\nb").is_err());
+    }
+
+    #[test]
+    fn test_roots() {
+        assert_eq!(root_user("/home/7").unwrap(), 7);
+        assert_eq!(root_user("/home/7/docs").unwrap(), 7);
+        assert!(root_user("/home").is_err());
+        assert!(root_user("/system").is_err());
+
+        assert!(is_excluded("/home/7/.trash"));
+        assert!(is_excluded("/home/7/.trash/100-0-a.txt"));
+        assert!(!is_excluded("/home/7/docs/.trash"));
+        assert!(!is_excluded("/home/7"));
+    }
+
+    #[test]
+    fn test_manifest() {
+        let mut manifest = SnapshotManifest::new("100-0".into(), "/home/7".into(), None, 100);
+        for entry in [
+            file("/home/7/docs/b.txt", 3),
+            dir("/home/7/docs"),
+            dir("/home/7"),
+            file("/home/7/a b", 2),
+        ] {
+            manifest.push(entry).unwrap();
+        }
+        manifest.finish();
+
+        let paths: Vec<&str> = manifest
+            .entries
+            .iter()
+            .map(|e| e.inode.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "/home/7",
+                "/home/7/a b",
+                "/home/7/docs",
+                "/home/7/docs/b.txt"
+            ]
+        );
+        assert_eq!(manifest.info.files, 2);
+        assert_eq!(manifest.info.size, 5);
+        assert!(manifest.entry("/home/7/docs").is_some());
+        assert!(manifest.entry("/home/7/c.txt").is_none());
+        assert_eq!(manifest.blobs().count(), 2);
+
+        let decoded = SnapshotManifest::decode(&manifest.encode().unwrap()).unwrap();
+        assert_eq!(decoded.info, manifest.info);
+        assert_eq!(decoded.entries.len(), 4);
+        assert_eq!(decoded.entries[3].content, Some([3; 32]));
+
+        manifest.version = 9;
+        assert!(SnapshotManifest::decode(&manifest.encode().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Content-addressed, reference-counted blobs.
+//!
+//! Files linked to one another share one stored copy of their content. The
+//! copy is a blob record named by the SHA-256 of its bytes, and each file's
+//! content key holds a small link record naming the blob instead of the
+//! content itself. A refcount record beside the blob counts the links, and
+//! the blob goes once the last of them does.
+//!
+//! # Record formats (version 1)
+//!
+//! ```text
+//! link:     magic "ZLNK" (4) | version (1) | blob address (32)
+//! refcount: link count, LE (8)
+//! ```
+//!
+//! A blob holds the content record exactly as it was stored under the first
+//! file's content key: plaintext, sealed or a chunk manifest. The address
+//! covers those bytes, so linking the same record twice finds the same blob.
+//! Blobs are never links themselves.
+//!
+//! Like manifests, a link record is not sealed; it says nothing the inode
+//! doesn't. Unowned content that happens to begin with the magic reads as a
+//! damaged link.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::core::VfsError;
+
+/// SHA-256 of a blob record.
+pub type BlobHash = [u8; 32];
+
+/// First bytes of every link record.
+pub const LINK_MAGIC: [u8; 4] = *b"ZLNK";
+
+/// Format version written by [`encode_link`].
+pub const LINK_VERSION: u8 = 1;
+
+const VERSION_AT: usize = LINK_MAGIC.len();
+const HASH_AT: usize = VERSION_AT + 1;
+const LINK_LEN: usize = HASH_AT + 32;
+
+/// Whether a content record is a link rather than the content itself.
+pub fn is_link(record: &[u8]) -> bool {
+    record.starts_with(&LINK_MAGIC)
+}
+
+/// Address of a content record once it is stored as a blob.
+pub fn content_address(record: &[u8]) -> BlobHash {
+    Sha256::digest(record).into()
+}
+
+/// Link record naming the blob at `hash`.
+pub fn encode_link(hash: &BlobHash) -> Vec<u8> {
+    let mut record = Vec::with_capacity(LINK_LEN);
+    record.extend_from_slice(&LINK_MAGIC);
+    record.push(LINK_VERSION);
+    record.extend_from_slice(hash);
+    record
+}
+
+/// Blob address in a record written by [`encode_link`].
+pub fn decode_link(record: &[u8]) -> Result<BlobHash, VfsError> {
+    if !is_link(record) {
+        return Err(link_error("missing magic"));
+    }
+    if record.len() != LINK_LEN {
+        return Err(link_error(&format!("bad length {}", record.len())));
+    }
+    if record[VERSION_AT] != LINK_VERSION {
+        return Err(link_error(&format!(
+            "unknown version {}",
+            record[VERSION_AT]
+        )));
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&record[HASH_AT..]);
+    Ok(hash)
+}
+
+/// Prefix of every blob's storage key.
+pub const BLOB_KEY_PREFIX: &str = "blob:";
+
+/// Storage key of the blob at `hash`.
+pub fn blob_key(hash: &BlobHash) -> String {
+    format!("{}{}", BLOB_KEY_PREFIX, to_hex(hash))
+}
+
+/// Address of the blob stored under `key`, if it is a blob key.
+pub fn blob_of_key(key: &str) -> Option<BlobHash> {
+    let hex = key.strip_prefix(BLOB_KEY_PREFIX)?;
+    if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut hash = [0u8; 32];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(hash)
+}
+
+/// Storage key of the link count of the blob at `hash`.
+pub fn refcount_key(hash: &BlobHash) -> String {
+    format!("blobref:{}", to_hex(hash))
+}
+
+/// Serialize a link count.
+pub fn encode_refcount(count: u64) -> Vec<u8> {
+    count.to_le_bytes().to_vec()
+}
+
+/// Parse a record written by [`encode_refcount`].
+pub fn decode_refcount(record: &[u8]) -> Result<u64, VfsError> {
+    let bytes: [u8; 8] = record.try_into().map_err(|_| {
+        VfsError::StorageError(format!(
+            "Blob refcount corrupt: bad length {}",
+            record.len()
+        ))
+    })?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Hash as lowercase hex, for keys and logs.
+pub fn to_hex(hash: &BlobHash) -> String {
+    hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn link_error(reason: &str) -> VfsError {
+    VfsError::StorageError(format!("Link record corrupt: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_round_trip() {
+        let hash = content_address(b"hello");
+        let record = encode_link(&hash);
+        assert!(is_link(&record));
+        assert_eq!(decode_link(&record).unwrap(), hash);
+
+        assert!(!is_link(b"plain text"));
+        assert!(decode_link(&record[..record.len() - 1]).is_err());
+        let mut bad = record;
+        bad[VERSION_AT] = 9;
+        assert!(decode_link(&bad).is_err());
+    }
+
+    #[test]
+    fn test_content_address() {
+        assert_eq!(content_address(b"same"), content_address(b"same"));
+        assert_ne!(content_address(b"same"), content_address(b"other"));
+        assert_eq!(
+            blob_key(&content_address(b"")),
+            "blob:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert!(refcount_key(&[0xab; 32]).starts_with("blobref:abab"));
+    }
+
+    #[test]
+    fn test_blob_of_key() {
+        let hash = content_address(b"hello");
+        assert_eq!(blob_of_key(&blob_key(&hash)), Some(hash));
+        assert_eq!(blob_of_key(&refcount_key(&hash)), None);
+        assert_eq!(blob_of_key("blob:abcd"), None);
+        assert_eq!(blob_of_key(&format!("blob:{}", "zz".repeat(32))), None);
+    }
+
+    #[test]
+    fn test_refcount_codec() {
+        assert_eq!(decode_refcount(&encode_refcount(3)).unwrap(), 3);
+        assert!(decode_refcount(&[1, 2, 3]).is_err());
+    }
+}
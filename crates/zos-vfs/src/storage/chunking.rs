@@ -0,0 +1,274 @@
+//! Chunked storage for large file content.
+//!
+//! Content of up to [`CHUNK_SIZE`] bytes is stored as one record under the
+//! file's content key. Anything larger is split into fixed-size chunks, each
+//! its own record, and the content key holds a small manifest naming them.
+//! A read fetches only the chunks its range covers and a write replaces only
+//! the chunks it touches, so neither has to hold the whole file at once.
+//!
+//! # Manifest format (version 1)
+//!
+//! ```text
+//! magic "ZCHK" (4) | version (1) | content id, LE (8)
+//! | size, LE (8) | chunk size, LE (4)
+//! ```
+//!
+//! Chunk records are keyed by the content id rather than the path (see
+//! [`ChunkManifest::chunk_key`]), so a rename moves only the manifest.
+//! Replacing a whole file picks a new id: the new chunks are all stored
+//! before the new manifest replaces the old one, and the old chunks are
+//! deleted after that.
+//!
+//! Each chunk of an owned file is sealed on its own (see
+//! [`encryption`](super::encryption)). The manifest is not; it holds
+//! nothing the inode doesn't already show. As with sealed blobs, unowned
+//! content that happens to begin with the magic reads as a damaged
+//! manifest.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::core::VfsError;
+
+/// Size of every chunk but the last.
+pub const CHUNK_SIZE: u32 = 64 * 1024;
+
+/// Largest file chunked storage accepts (1 GiB).
+pub const MAX_CHUNKED_SIZE: u64 = 1 << 30;
+
+/// First bytes of every manifest record.
+pub const MANIFEST_MAGIC: [u8; 4] = *b"ZCHK";
+
+/// Format version written by [`ChunkManifest::encode`].
+pub const MANIFEST_VERSION: u8 = 1;
+
+const VERSION_AT: usize = MANIFEST_MAGIC.len();
+const ID_AT: usize = VERSION_AT + 1;
+const SIZE_AT: usize = ID_AT + 8;
+const CHUNK_SIZE_AT: usize = SIZE_AT + 8;
+const MANIFEST_LEN: usize = CHUNK_SIZE_AT + 4;
+
+/// Whether content of `len` bytes is stored in chunks.
+pub fn is_chunked(len: u64) -> bool {
+    len > CHUNK_SIZE as u64
+}
+
+/// Whether a content record is a manifest rather than the content itself.
+pub fn is_manifest(record: &[u8]) -> bool {
+    record.starts_with(&MANIFEST_MAGIC)
+}
+
+/// Where a chunked file's content lives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkManifest {
+    /// Names the chunk records; new for every whole-file write
+    pub id: u64,
+    /// Content size in bytes
+    pub size: u64,
+    /// Bytes per chunk (the last may be shorter)
+    pub chunk_size: u32,
+}
+
+impl ChunkManifest {
+    /// Manifest for `size` bytes stored under `id` in [`CHUNK_SIZE`] chunks.
+    pub fn new(id: u64, size: u64) -> Self {
+        Self {
+            id,
+            size,
+            chunk_size: CHUNK_SIZE,
+        }
+    }
+
+    /// Serialize as a content record.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut record = Vec::with_capacity(MANIFEST_LEN);
+        record.extend_from_slice(&MANIFEST_MAGIC);
+        record.push(MANIFEST_VERSION);
+        record.extend_from_slice(&self.id.to_le_bytes());
+        record.extend_from_slice(&self.size.to_le_bytes());
+        record.extend_from_slice(&self.chunk_size.to_le_bytes());
+        record
+    }
+
+    /// Parse a content record written by [`encode`](Self::encode).
+    pub fn decode(record: &[u8]) -> Result<Self, VfsError> {
+        if !is_manifest(record) {
+            return Err(manifest_error("missing magic"));
+        }
+        if record.len() != MANIFEST_LEN {
+            return Err(manifest_error(&format!("bad length {}", record.len())));
+        }
+        if record[VERSION_AT] != MANIFEST_VERSION {
+            return Err(manifest_error(&format!(
+                "unknown version {}",
+                record[VERSION_AT]
+            )));
+        }
+        let manifest = Self {
+            id: u64::from_le_bytes(le_bytes(&record[ID_AT..SIZE_AT])),
+            size: u64::from_le_bytes(le_bytes(&record[SIZE_AT..CHUNK_SIZE_AT])),
+            chunk_size: u32::from_le_bytes(le_bytes(&record[CHUNK_SIZE_AT..])),
+        };
+        if manifest.chunk_size == 0 {
+            return Err(manifest_error("zero chunk size"));
+        }
+        Ok(manifest)
+    }
+
+    /// Storage key of chunk `index`.
+    pub fn chunk_key(&self, index: u32) -> String {
+        format!("chunk:{:016x}:{}", self.id, index)
+    }
+
+    /// Number of chunks holding the content.
+    pub fn chunk_count(&self) -> u32 {
+        self.size.div_ceil(self.chunk_size as u64) as u32
+    }
+
+    /// Offset of chunk `index` within the file.
+    pub fn chunk_start(&self, index: u32) -> u64 {
+        index as u64 * self.chunk_size as u64
+    }
+
+    /// Length of chunk `index` (0 past the end).
+    pub fn chunk_len(&self, index: u32) -> usize {
+        let start = self.chunk_start(index);
+        self.size.saturating_sub(start).min(self.chunk_size as u64) as usize
+    }
+
+    /// Chunks holding any of the `len` bytes at `offset`, clipped to the
+    /// content.
+    pub fn chunks_covering(&self, offset: u64, len: u64) -> Range<u32> {
+        let end = offset.saturating_add(len).min(self.size);
+        if offset >= end {
+            return 0..0;
+        }
+        let chunk_size = self.chunk_size as u64;
+        (offset / chunk_size) as u32..end.div_ceil(chunk_size) as u32
+    }
+
+    /// Chunk `index` of `content`, which is the whole file.
+    pub fn chunk_of<'a>(&self, content: &'a [u8], index: u32) -> &'a [u8] {
+        let start = (self.chunk_start(index) as usize).min(content.len());
+        let end = start
+            .saturating_add(self.chunk_len(index))
+            .min(content.len());
+        &content[start..end]
+    }
+
+    /// Whether writing `len` bytes at `offset` leaves any of chunk `index`'s
+    /// current bytes in place, so the chunk has to be read first.
+    pub fn keeps_bytes(&self, index: u32, offset: u64, len: u64) -> bool {
+        let chunk_len = self.chunk_len(index) as u64;
+        if chunk_len == 0 {
+            return false;
+        }
+        let start = self.chunk_start(index);
+        offset > start || offset.saturating_add(len) < start + chunk_len
+    }
+
+    /// Chunk `index` once `data` is written at `offset`, starting from its
+    /// current bytes `old`.
+    ///
+    /// `self` describes the file after the write; bytes between the old end
+    /// and `offset` read as zero.
+    pub fn patch_chunk(&self, index: u32, old: &[u8], offset: u64, data: &[u8]) -> Vec<u8> {
+        let start = self.chunk_start(index);
+        let mut chunk = old.to_vec();
+        chunk.resize(self.chunk_len(index), 0);
+        let end = offset.saturating_add(data.len() as u64);
+        let from = offset.max(start);
+        let to = end.min(start + chunk.len() as u64);
+        if from < to {
+            chunk[(from - start) as usize..(to - start) as usize]
+                .copy_from_slice(&data[(from - offset) as usize..(to - offset) as usize]);
+        }
+        chunk
+    }
+}
+
+fn manifest_error(reason: &str) -> VfsError {
+    VfsError::StorageError(format!("Chunk manifest corrupt: {}", reason))
+}
+
+fn le_bytes<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut out = [0u8; N];
+    out.copy_from_slice(bytes);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let manifest = ChunkManifest::new(0x1234_5678_9abc_def0, 200_000);
+        let record = manifest.encode();
+        assert!(is_manifest(&record));
+        assert_eq!(ChunkManifest::decode(&record).unwrap(), manifest);
+        assert_eq!(manifest.chunk_key(3), "chunk:123456789abcdef0:3");
+
+        assert!(!is_manifest(b"plain text"));
+        assert!(ChunkManifest::decode(&record[..record.len() - 1]).is_err());
+        let mut bad = record.clone();
+        bad[VERSION_AT] = 9;
+        assert!(ChunkManifest::decode(&bad).is_err());
+        let mut zero = record;
+        zero[CHUNK_SIZE_AT..].fill(0);
+        assert!(ChunkManifest::decode(&zero).is_err());
+    }
+
+    #[test]
+    fn test_chunk_layout() {
+        assert!(!is_chunked(CHUNK_SIZE as u64));
+        assert!(is_chunked(CHUNK_SIZE as u64 + 1));
+
+        let manifest = ChunkManifest {
+            id: 1,
+            size: 25,
+            chunk_size: 10,
+        };
+        assert_eq!(manifest.chunk_count(), 3);
+        assert_eq!(manifest.chunk_len(1), 10);
+        assert_eq!(manifest.chunk_len(2), 5);
+        assert_eq!(manifest.chunk_len(3), 0);
+        assert_eq!(manifest.chunks_covering(0, 25), 0..3);
+        assert_eq!(manifest.chunks_covering(9, 2), 0..2);
+        assert_eq!(manifest.chunks_covering(10, 10), 1..2);
+        assert_eq!(manifest.chunks_covering(22, 100), 2..3);
+        assert_eq!(manifest.chunks_covering(25, 4), 0..0);
+        assert_eq!(manifest.chunks_covering(3, 0), 0..0);
+
+        let content: Vec<u8> = (0..25).collect();
+        assert_eq!(manifest.chunk_of(&content, 1), &content[10..20]);
+        assert_eq!(manifest.chunk_of(&content, 2), &content[20..25]);
+    }
+
+    #[test]
+    fn test_patch_chunk() {
+        let old = ChunkManifest {
+            id: 1,
+            size: 15,
+            chunk_size: 10,
+        };
+        // Covers chunk 0 entirely, keeps part of chunk 1
+        assert!(!old.keeps_bytes(0, 0, 12));
+        assert!(old.keeps_bytes(1, 0, 12));
+        assert!(!old.keeps_bytes(1, 10, 5));
+        // Nothing stored past the end
+        assert!(!old.keeps_bytes(2, 20, 1));
+
+        // Write 4 bytes at 18: chunk 1 grows and gets a zero gap
+        let new = ChunkManifest { size: 22, ..old };
+        let chunk = new.patch_chunk(1, &[1, 2, 3, 4, 5], 18, &[9, 9, 9, 9]);
+        assert_eq!(chunk, vec![1, 2, 3, 4, 5, 0, 0, 0, 9, 9]);
+        let chunk = new.patch_chunk(2, &[], 18, &[9, 9, 9, 9]);
+        assert_eq!(chunk, vec![9, 9]);
+        // Chunk the write doesn't reach is only extended
+        assert_eq!(new.patch_chunk(0, &[7; 10], 18, &[9]), vec![7; 10]);
+    }
+}
@@ -0,0 +1,332 @@
+//! Content encryption for stored file blobs.
+//!
+//! Each user has one content key, kept in the KeystoreService at
+//! [`ContentKey::storage_path`]. It never encrypts file content directly:
+//! every sealed blob gets a fresh data key, which is wrapped (encrypted) by
+//! the user's content key and stored in the blob's header. Both layers use
+//! AES-256-GCM.
+//!
+//! # Record format (version 1)
+//!
+//! ```text
+//! magic "ZENC" (4) | version (1) | user_id, LE (16)
+//! | key nonce (12) | wrapped data key (48)
+//! | content nonce (12) | ciphertext + tag
+//! ```
+//!
+//! The header is authenticated along with the content, so a blob can't be
+//! moved to another user's key or re-labelled with another version. It is
+//! not bound to a path, which lets rename copy blobs as they are.
+//!
+//! Blobs written before encryption existed have no header. They are read as
+//! plaintext (format version 0) until rewritten. The flip side is that an
+//! unsealed blob that happens to begin with the magic reads as a damaged
+//! sealed one; only files without an owner are stored unsealed.
+//!
+//! Sealing takes its randomness from the caller so this module stays
+//! deterministic and free of platform dependencies.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{UserId, VfsError};
+
+/// Format version written by [`seal`].
+pub const CONTENT_FORMAT_VERSION: u8 = 1;
+
+/// Format version reported for blobs without a header.
+pub const PLAINTEXT_FORMAT_VERSION: u8 = 0;
+
+/// First bytes of every sealed blob.
+pub const SEALED_MAGIC: [u8; 4] = *b"ZENC";
+
+/// Random bytes [`seal`] needs: data key, key nonce, content nonce.
+pub const SEAL_RANDOM_BYTES: usize = KEY_LEN + NONCE_LEN + NONCE_LEN;
+
+/// Bytes a sealed blob adds to its content.
+pub const SEALED_OVERHEAD: usize = HEADER_LEN + TAG_LEN;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+const VERSION_AT: usize = SEALED_MAGIC.len();
+const USER_AT: usize = VERSION_AT + 1;
+const KEY_NONCE_AT: usize = USER_AT + 16;
+const WRAPPED_KEY_AT: usize = KEY_NONCE_AT + NONCE_LEN;
+const CONTENT_NONCE_AT: usize = WRAPPED_KEY_AT + KEY_LEN + TAG_LEN;
+const HEADER_LEN: usize = CONTENT_NONCE_AT + NONCE_LEN;
+
+/// A user's content key.
+///
+/// The bytes are zeroed on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ContentKey([u8; KEY_LEN]);
+
+impl ContentKey {
+    /// Wrap raw key bytes.
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Keystore path of `user_id`'s content key.
+    pub fn storage_path(user_id: UserId) -> String {
+        format!("/keys/{}/vfs/content_key.json", user_id)
+    }
+
+    /// The form kept in the keystore.
+    pub fn to_stored(&self, created_at: u64) -> StoredContentKey {
+        StoredContentKey {
+            version: CONTENT_FORMAT_VERSION,
+            key: self.0.iter().map(|b| format!("{:02x}", b)).collect(),
+            created_at,
+        }
+    }
+
+    /// Recover a key read from the keystore.
+    pub fn from_stored(stored: &StoredContentKey) -> Result<Self, VfsError> {
+        let hex = stored.key.as_bytes();
+        if hex.len() != KEY_LEN * 2 {
+            return Err(VfsError::DecryptionError(format!(
+                "Content key has {} hex digits, expected {}",
+                hex.len(),
+                KEY_LEN * 2
+            )));
+        }
+        let mut bytes = [0u8; KEY_LEN];
+        for (byte, pair) in bytes.iter_mut().zip(hex.chunks(2)) {
+            let pair = core::str::from_utf8(pair).unwrap_or("");
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| {
+                VfsError::DecryptionError(String::from("Content key is not valid hex"))
+            })?;
+        }
+        Ok(Self(bytes))
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.0))
+    }
+}
+
+impl Drop for ContentKey {
+    fn drop(&mut self) {
+        self.0.fill(0);
+    }
+}
+
+impl core::fmt::Debug for ContentKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("ContentKey(..)")
+    }
+}
+
+/// A content key as stored in the keystore.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredContentKey {
+    /// Record format version the key was created for
+    pub version: u8,
+    /// Key bytes, hex
+    pub key: String,
+    /// Creation time (wallclock ms)
+    pub created_at: u64,
+}
+
+/// Whether `blob` is a sealed record rather than legacy plaintext.
+pub fn is_sealed(blob: &[u8]) -> bool {
+    blob.starts_with(&SEALED_MAGIC)
+}
+
+/// On-disk format version of `blob`.
+pub fn format_version(blob: &[u8]) -> u8 {
+    match blob.get(VERSION_AT) {
+        Some(&version) if is_sealed(blob) => version,
+        _ => PLAINTEXT_FORMAT_VERSION,
+    }
+}
+
+/// The user whose content key opens a sealed `blob`.
+pub fn sealed_for(blob: &[u8]) -> Result<UserId, VfsError> {
+    check_header(blob)?;
+    let mut user = [0u8; 16];
+    user.copy_from_slice(&blob[USER_AT..KEY_NONCE_AT]);
+    Ok(UserId::from_le_bytes(user))
+}
+
+/// Encrypt `plaintext` for `user_id` under their content key.
+pub fn seal(
+    key: &ContentKey,
+    user_id: UserId,
+    plaintext: &[u8],
+    random: &[u8; SEAL_RANDOM_BYTES],
+) -> Result<Vec<u8>, VfsError> {
+    let (data_key, nonces) = random.split_at(KEY_LEN);
+    let (key_nonce, content_nonce) = nonces.split_at(NONCE_LEN);
+
+    let mut blob = Vec::with_capacity(plaintext.len() + SEALED_OVERHEAD);
+    blob.extend_from_slice(&SEALED_MAGIC);
+    blob.push(CONTENT_FORMAT_VERSION);
+    blob.extend_from_slice(&user_id.to_le_bytes());
+
+    let wrapped = key
+        .cipher()
+        .encrypt(
+            Nonce::from_slice(key_nonce),
+            Payload {
+                msg: data_key,
+                aad: &blob,
+            },
+        )
+        .map_err(|_| VfsError::EncryptionError(String::from("Data key wrap failed")))?;
+    blob.extend_from_slice(key_nonce);
+    blob.extend_from_slice(&wrapped);
+    blob.extend_from_slice(content_nonce);
+
+    let mut key_bytes = [0u8; KEY_LEN];
+    key_bytes.copy_from_slice(data_key);
+    let data_key = ContentKey::from_bytes(key_bytes);
+    let ciphertext = data_key
+        .cipher()
+        .encrypt(
+            Nonce::from_slice(content_nonce),
+            Payload {
+                msg: plaintext,
+                aad: &blob,
+            },
+        )
+        .map_err(|_| VfsError::EncryptionError(String::from("Content encryption failed")))?;
+    blob.extend_from_slice(&ciphertext);
+    Ok(blob)
+}
+
+/// Decrypt a sealed `blob` with its owner's content key.
+pub fn open(key: &ContentKey, blob: &[u8]) -> Result<Vec<u8>, VfsError> {
+    check_header(blob)?;
+    let unwrapped = key
+        .cipher()
+        .decrypt(
+            Nonce::from_slice(&blob[KEY_NONCE_AT..WRAPPED_KEY_AT]),
+            Payload {
+                msg: &blob[WRAPPED_KEY_AT..CONTENT_NONCE_AT],
+                aad: &blob[..KEY_NONCE_AT],
+            },
+        )
+        .map_err(|_| VfsError::DecryptionError(String::from("Data key unwrap failed")))?;
+    let mut data_key = [0u8; KEY_LEN];
+    data_key.copy_from_slice(&unwrapped);
+    let data_key = ContentKey::from_bytes(data_key);
+
+    data_key
+        .cipher()
+        .decrypt(
+            Nonce::from_slice(&blob[CONTENT_NONCE_AT..HEADER_LEN]),
+            Payload {
+                msg: &blob[HEADER_LEN..],
+                aad: &blob[..HEADER_LEN],
+            },
+        )
+        .map_err(|_| VfsError::DecryptionError(String::from("Content authentication failed")))
+}
+
+fn check_header(blob: &[u8]) -> Result<(), VfsError> {
+    if !is_sealed(blob) {
+        return Err(VfsError::DecryptionError(String::from(
+            "Content is not sealed",
+        )));
+    }
+    match format_version(blob) {
+        CONTENT_FORMAT_VERSION if blob.len() >= SEALED_OVERHEAD => Ok(()),
+        CONTENT_FORMAT_VERSION => Err(VfsError::DecryptionError(String::from(
+            "Sealed content is truncated",
+        ))),
+        version => Err(VfsError::DecryptionError(format!(
+            "Unsupported content format version {}",
+            version
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: UserId = 0x0123_4567_89ab_cdef_0011_2233_4455_6677;
+
+    fn random(fill: u8) -> [u8; SEAL_RANDOM_BYTES] {
+        [fill; SEAL_RANDOM_BYTES]
+    }
+
+    #[test]
+    fn test_seal_open_round_trip() {
+        let key = ContentKey::from_bytes([7; 32]);
+        let blob = seal(&key, USER, b"hello world", &random(1)).unwrap();
+
+        assert!(is_sealed(&blob));
+        assert_eq!(format_version(&blob), CONTENT_FORMAT_VERSION);
+        assert_eq!(blob.len(), 11 + SEALED_OVERHEAD);
+        assert_eq!(sealed_for(&blob).unwrap(), USER);
+        assert_eq!(open(&key, &blob).unwrap(), b"hello world");
+
+        let empty = seal(&key, USER, b"", &random(2)).unwrap();
+        assert_eq!(open(&key, &empty).unwrap(), b"");
+    }
+
+    #[test]
+    fn test_open_rejects_wrong_key_and_tampering() {
+        let key = ContentKey::from_bytes([7; 32]);
+        let blob = seal(&key, USER, b"secret", &random(1)).unwrap();
+
+        let other = ContentKey::from_bytes([8; 32]);
+        assert!(matches!(
+            open(&other, &blob),
+            Err(VfsError::DecryptionError(_))
+        ));
+
+        // Every byte of the header and body is authenticated
+        for at in SEALED_MAGIC.len() + 1..blob.len() {
+            let mut tampered = blob.clone();
+            tampered[at] ^= 1;
+            assert!(open(&key, &tampered).is_err(), "byte {} not covered", at);
+        }
+    }
+
+    #[test]
+    fn test_legacy_and_unknown_formats() {
+        let key = ContentKey::from_bytes([7; 32]);
+        assert!(!is_sealed(b"plain text"));
+        assert_eq!(format_version(b"plain text"), PLAINTEXT_FORMAT_VERSION);
+        assert_eq!(format_version(b""), PLAINTEXT_FORMAT_VERSION);
+        assert!(open(&key, b"plain text").is_err());
+
+        let mut future = seal(&key, USER, b"data", &random(1)).unwrap();
+        future[VERSION_AT] = CONTENT_FORMAT_VERSION + 1;
+        assert_eq!(format_version(&future), CONTENT_FORMAT_VERSION + 1);
+        assert!(sealed_for(&future).is_err());
+
+        assert!(sealed_for(&SEALED_MAGIC).is_err());
+    }
+
+    #[test]
+    fn test_stored_key_round_trip() {
+        let key = ContentKey::from_bytes([0xa5; 32]);
+        let stored = key.to_stored(1000);
+        assert_eq!(stored.key.len(), 64);
+        let json = serde_json::to_vec(&stored).unwrap();
+        let back: StoredContentKey = serde_json::from_slice(&json).unwrap();
+        assert_eq!(ContentKey::from_stored(&back).unwrap(), key);
+
+        let bad = StoredContentKey {
+            version: 1,
+            key: String::from("zz"),
+            created_at: 0,
+        };
+        assert!(ContentKey::from_stored(&bad).is_err());
+        assert_eq!(
+            ContentKey::storage_path(42),
+            "/keys/42/vfs/content_key.json"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Content hashes for integrity checks.
+//!
+//! A whole-file write records the SHA-256 of the plaintext in the file's
+//! inode (`Inode::content_hash`). The hash is optional: inodes written
+//! before it existed have none, and a write through a handle drops it
+//! rather than rehash the whole file. A file with no recorded hash is
+//! never reported corrupt.
+//!
+//! The hash covers the content as the client sees it, not the stored
+//! records, so it survives sealing, chunking and hard links unchanged and
+//! can be compared with hashes computed anywhere else (an update bundle's,
+//! say).
+
+use sha2::{Digest, Sha256};
+
+use super::blobs::to_hex;
+use crate::core::{StorageErrorKind, VfsError};
+use crate::ipc::FileHash;
+
+/// SHA-256 of a file's plaintext.
+pub type ContentHash = [u8; 32];
+
+/// Hash of `content` as a whole.
+pub fn content_hash(content: &[u8]) -> ContentHash {
+    Sha256::digest(content).into()
+}
+
+/// Check a computed hash against the one an inode records, if any.
+pub fn verify(expected: Option<&ContentHash>, actual: &ContentHash) -> Result<(), VfsError> {
+    match expected {
+        Some(expected) if expected != actual => Err(VfsError::storage_error_with_context(
+            StorageErrorKind::ChunkCorrupt {
+                chunk_index: None,
+                expected_hash: Some(to_hex(expected)),
+            },
+            "Content does not match its recorded hash",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Hash as reported to clients.
+pub fn file_hash(hash: &ContentHash, size: u64) -> FileHash {
+    FileHash {
+        sha256: to_hex(hash),
+        size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let hash = content_hash(b"hello");
+        assert!(verify(None, &hash).is_ok());
+        assert!(verify(Some(&hash), &hash).is_ok());
+
+        let error = verify(Some(&content_hash(b"other")), &hash).unwrap_err();
+        assert!(error.is_corrupt());
+        assert!(!VfsError::NotFound.is_corrupt());
+    }
+
+    #[test]
+    fn test_file_hash() {
+        let hash = file_hash(&content_hash(b"abc"), 3);
+        assert_eq!(
+            hash.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(hash.size, 3);
+    }
+}
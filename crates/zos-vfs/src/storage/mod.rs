@@ -1,6 +1,14 @@
 //! Storage types for the VFS layer.
 //!
-//! Defines quota management and storage usage tracking.
+//! Defines quota management and storage usage tracking. Content blob
+//! encryption lives in [`encryption`], the chunk layout of large files in
+//! [`chunking`], the shared content behind hard links in [`blobs`], and
+//! the content hashes files are checked against in [`integrity`].
+
+pub mod blobs;
+pub mod chunking;
+pub mod encryption;
+pub mod integrity;
 
 use serde::{Deserialize, Serialize};
 
@@ -58,7 +66,7 @@ impl StorageUsage {
 }
 
 /// Per-user storage quota.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StorageQuota {
     /// User ID
     pub user_id: UserId,
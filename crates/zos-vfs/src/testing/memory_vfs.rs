@@ -8,8 +8,8 @@ use alloc::vec::Vec;
 use core::cell::RefCell;
 
 use crate::core::{
-    filename, join_path, normalize_path, parent_path, DirEntry, FilePermissions, Inode, InodeType,
-    UserId, VfsError,
+    filename, is_under, join_path, normalize_path, parent_path, DirEntry, FilePermissions, Inode,
+    InodeType, UserId, VfsError,
 };
 use crate::service::VfsService;
 use crate::storage::{StorageQuota, StorageUsage};
@@ -365,11 +365,24 @@ impl VfsService for MemoryVfs {
         let from = normalize_path(from)?;
         let to = normalize_path(to)?;
 
-        // Get source inode
-        let inode = {
-            let inodes = self.inodes.borrow();
-            inodes.get(&from).cloned().ok_or(VfsError::NotFound)?
-        };
+        if from == "/" || to == "/" {
+            return Err(VfsError::InvalidPath(String::from("Cannot rename root")));
+        }
+        if !self.inodes.borrow().contains_key(&from) {
+            return Err(VfsError::NotFound);
+        }
+        if from == to {
+            return Ok(());
+        }
+        // A directory can't be moved into its own subtree
+        if is_under(&to, &from) {
+            return Err(VfsError::InvalidPath(String::from(
+                "Cannot move a directory into itself",
+            )));
+        }
+        if self.inodes.borrow().contains_key(&to) {
+            return Err(VfsError::AlreadyExists);
+        }
 
         // Check destination parent exists
         let to_parent = parent_path(&to);
@@ -382,22 +395,34 @@ impl VfsService for MemoryVfs {
             }
         }
 
-        // Move content if file
-        let content = self.content.borrow_mut().remove(&from);
-
-        // Update inode with new path
-        let mut new_inode = inode;
-        new_inode.path = to.clone();
-        new_inode.parent_path = to_parent;
-        new_inode.name = String::from(filename(&to));
-        new_inode.modified_at = self.get_now();
+        // The entry and, for a directory, everything below it
+        let moved: Vec<String> = self
+            .inodes
+            .borrow()
+            .keys()
+            .filter(|path| is_under(path, &from))
+            .cloned()
+            .collect();
 
-        // Remove old, insert new
-        self.inodes.borrow_mut().remove(&from);
-        self.inodes.borrow_mut().insert(to.clone(), new_inode);
+        let now = self.get_now();
+        let mut inodes = self.inodes.borrow_mut();
+        let mut content = self.content.borrow_mut();
+        for old in moved {
+            let new = alloc::format!("{}{}", to, &old[from.len()..]);
+            let mut inode = inodes.remove(&old).ok_or(VfsError::NotFound)?;
+            if old == from {
+                inode.parent_path = to_parent.clone();
+                inode.name = String::from(filename(&to));
+                inode.modified_at = now;
+            } else {
+                inode.parent_path = parent_path(&new);
+            }
+            inode.path = new.clone();
+            inodes.insert(new.clone(), inode);
 
-        if let Some(c) = content {
-            self.content.borrow_mut().insert(to, c);
+            if let Some(c) = content.remove(&old) {
+                content.insert(new, c);
+            }
         }
 
         Ok(())
@@ -477,6 +502,7 @@ impl VfsService for MemoryVfs {
         let now = self.get_now();
 
         let inode = Inode {
+            schema_version: crate::schema::INODE_SCHEMA_VERSION,
             path: link_path.clone(),
             parent_path: parent,
             name: String::from(name),
@@ -491,6 +517,7 @@ impl VfsService for MemoryVfs {
             size: target.len() as u64,
             encrypted: false,
             content_hash: None,
+            xattrs: Default::default(),
         };
 
         self.inodes.borrow_mut().insert(link_path, inode);
@@ -674,6 +701,71 @@ mod tests {
         assert_eq!(vfs.read_file("/home/new.txt").unwrap(), b"content");
     }
 
+    #[test]
+    fn test_rename_across_directories() {
+        let vfs = MemoryVfs::new();
+
+        vfs.mkdir_p("/home/a").unwrap();
+        vfs.mkdir("/home/b").unwrap();
+        vfs.write_file("/home/a/file.txt", b"content").unwrap();
+
+        vfs.rename("/home/a/file.txt", "/home/b/moved.txt").unwrap();
+
+        let inode = vfs.stat("/home/b/moved.txt").unwrap();
+        assert_eq!(inode.parent_path, "/home/b");
+        assert_eq!(inode.name, "moved.txt");
+        assert_eq!(vfs.read_file("/home/b/moved.txt").unwrap(), b"content");
+        assert!(vfs.readdir("/home/a").unwrap().is_empty());
+        assert_eq!(vfs.readdir("/home/b").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rename_directory_moves_subtree() {
+        let vfs = MemoryVfs::new();
+
+        vfs.mkdir_p("/home/old/sub").unwrap();
+        vfs.write_file("/home/old/sub/file.txt", b"deep").unwrap();
+        vfs.mkdir("/home/oldish").unwrap();
+
+        vfs.rename("/home/old", "/home/new").unwrap();
+
+        assert!(!vfs.exists("/home/old").unwrap());
+        assert!(!vfs.exists("/home/old/sub/file.txt").unwrap());
+        // A sibling sharing the name prefix stays put
+        assert!(vfs.exists("/home/oldish").unwrap());
+        let inode = vfs.stat("/home/new/sub/file.txt").unwrap();
+        assert_eq!(inode.parent_path, "/home/new/sub");
+        assert_eq!(vfs.read_file("/home/new/sub/file.txt").unwrap(), b"deep");
+    }
+
+    #[test]
+    fn test_rename_rejects_bad_targets() {
+        let vfs = MemoryVfs::new();
+
+        vfs.mkdir_p("/home/dir").unwrap();
+        vfs.write_file("/home/a.txt", b"a").unwrap();
+        vfs.write_file("/home/b.txt", b"b").unwrap();
+
+        assert!(matches!(
+            vfs.rename("/home/a.txt", "/home/b.txt"),
+            Err(VfsError::AlreadyExists)
+        ));
+        assert!(matches!(
+            vfs.rename("/home/dir", "/home/dir/inner"),
+            Err(VfsError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            vfs.rename("/home/a.txt", "/missing/a.txt"),
+            Err(VfsError::NotFound)
+        ));
+        assert!(matches!(
+            vfs.rename("/home/missing", "/home/c.txt"),
+            Err(VfsError::NotFound)
+        ));
+        assert_eq!(vfs.read_file("/home/b.txt").unwrap(), b"b");
+        assert_eq!(vfs.read_file("/home/a.txt").unwrap(), b"a");
+    }
+
     #[test]
     fn test_copy() {
         let vfs = MemoryVfs::new();
@@ -0,0 +1,153 @@
+//! Trash (soft delete) layout.
+//!
+//! A file or directory unlinked with `trash` set is moved instead of
+//! deleted, to `/home/{user}/.trash/{id}` of the user doing the unlink,
+//! along with anything inside it. The path it came from and the time it was
+//! trashed are kept as extended attributes on its inode, so they move with
+//! it and need no record of their own. Restoring moves it back and drops the
+//! attributes; purging deletes it for good.
+//!
+//! Trashed files keep their owner, permissions and quota charge until they
+//! are purged. The VFS service purges entries older than
+//! [`DEFAULT_TRASH_MAX_AGE_MS`] in the background.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use crate::core::{extract_user_id, is_under, Inode, UserId, VfsError};
+use crate::ipc::TrashEntry;
+
+/// Name of the trash directory in a user's home.
+pub const TRASH_DIR_NAME: &str = ".trash";
+
+/// Attribute holding the path a trashed file was unlinked from.
+pub const XATTR_ORIGINAL_PATH: &str = "trash.original_path";
+
+/// Attribute holding when a file was trashed (wall-clock ms, decimal).
+pub const XATTR_DELETED_AT: &str = "trash.deleted_at";
+
+/// Age after which trashed files are purged (30 days).
+pub const DEFAULT_TRASH_MAX_AGE_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+
+/// The trash directory of `user_id`.
+pub fn trash_dir(user_id: UserId) -> String {
+    format!("/home/{}/{}", user_id, TRASH_DIR_NAME)
+}
+
+/// Path of the entry `id` in the trash of `user_id`.
+pub fn trash_path(user_id: UserId, id: &str) -> String {
+    format!("{}/{}", trash_dir(user_id), id)
+}
+
+/// Whether `path` is inside a user's trash directory.
+pub fn is_trashed(path: &str) -> bool {
+    extract_user_id(path).is_some_and(|user_id| {
+        let dir = trash_dir(user_id);
+        path != dir && is_under(path, &dir)
+    })
+}
+
+/// Name for a file trashed at `deleted_at`.
+///
+/// `seq` tells apart files with the same name trashed in the same
+/// millisecond; the name is kept so the directory stays readable.
+pub fn entry_id(deleted_at: u64, seq: u32, name: &str) -> String {
+    format!("{}-{}-{}", deleted_at, seq, name)
+}
+
+/// Check that `id` names an entry directly inside a trash directory.
+pub fn validate_entry_id(id: &str) -> Result<(), VfsError> {
+    if id.is_empty() || id == "." || id == ".." || id.contains('/') || id.contains('\0') {
+        return Err(VfsError::InvalidRequest(format!(
+            "Invalid trash entry: {:?}",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// Record on `inode` that it was trashed from `original_path`.
+pub fn mark_trashed(inode: &mut Inode, original_path: &str, deleted_at: u64) -> Result<(), VfsError> {
+    inode.set_xattr(XATTR_ORIGINAL_PATH, Some(original_path.to_string()))?;
+    inode.set_xattr(XATTR_DELETED_AT, Some(deleted_at.to_string()))
+}
+
+/// Drop the trash attributes from a restored inode.
+pub fn unmark_trashed(inode: &mut Inode) {
+    inode.xattrs.remove(XATTR_ORIGINAL_PATH);
+    inode.xattrs.remove(XATTR_DELETED_AT);
+}
+
+/// Path a trashed file was unlinked from.
+pub fn original_path(inode: &Inode) -> Option<&str> {
+    inode.xattrs.get(XATTR_ORIGINAL_PATH).map(String::as_str)
+}
+
+/// Describe a trashed entry; `None` if it wasn't put there by an unlink.
+pub fn entry_of(inode: &Inode) -> Option<TrashEntry> {
+    Some(TrashEntry {
+        id: inode.name.clone(),
+        original_path: original_path(inode)?.to_string(),
+        deleted_at: inode.xattrs.get(XATTR_DELETED_AT)?.parse().ok()?,
+        size: inode.size,
+    })
+}
+
+/// Whether a file trashed at `deleted_at` is older than `max_age_ms`.
+pub fn is_expired(deleted_at: u64, now: u64, max_age_ms: u64) -> bool {
+    now.saturating_sub(deleted_at) > max_age_ms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str) -> Inode {
+        let name = path.rsplit('/').next().unwrap().to_string();
+        Inode::new_file(path.into(), crate::parent_path(path), name, Some(7), 12, None, 1)
+    }
+
+    #[test]
+    fn test_trash_paths() {
+        assert_eq!(trash_dir(7), "/home/7/.trash");
+        assert_eq!(trash_path(7, "100-0-a.txt"), "/home/7/.trash/100-0-a.txt");
+        assert!(is_trashed("/home/7/.trash/100-0-a.txt"));
+        assert!(!is_trashed("/home/7/.trash"));
+        assert!(!is_trashed("/home/7/.trashcan/a.txt"));
+        assert!(!is_trashed("/home/7/docs/a.txt"));
+        assert!(!is_trashed("/tmp/.trash/a.txt"));
+    }
+
+    #[test]
+    fn test_entry_ids() {
+        assert_eq!(entry_id(100, 3, "a.txt"), "100-3-a.txt");
+        assert!(validate_entry_id("100-3-a.txt").is_ok());
+        for bad in ["", ".", "..", "a/b", "a\0"] {
+            assert!(validate_entry_id(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_mark_and_describe() {
+        let mut inode = file("/home/7/.trash/100-0-a.txt");
+        assert_eq!(entry_of(&inode), None);
+
+        mark_trashed(&mut inode, "/home/7/docs/a.txt", 100).unwrap();
+        let entry = entry_of(&inode).unwrap();
+        assert_eq!(entry.id, "100-0-a.txt");
+        assert_eq!(entry.original_path, "/home/7/docs/a.txt");
+        assert_eq!(entry.deleted_at, 100);
+        assert_eq!(entry.size, 12);
+
+        unmark_trashed(&mut inode);
+        assert!(inode.xattrs.is_empty());
+    }
+
+    #[test]
+    fn test_expiry() {
+        assert!(!is_expired(100, 150, 50));
+        assert!(is_expired(100, 151, 50));
+        // A clock that went backwards never expires anything
+        assert!(!is_expired(100, 50, 0));
+    }
+}
@@ -29,6 +29,13 @@
  * React app calls `window.ZosStorage.initSupervisor(supervisor)` to set up callbacks.
  * HAL calls the start* methods which perform IndexedDB operations and notify the
  * supervisor when complete.
+ *
+ * ## Cancellation
+ *
+ * HAL calls `cancelRequest(requestId)` when a process gives up on a request.
+ * Content reads and writes abort their IndexedDB transaction; reads, lists and
+ * exists checks report `notify_storage_cancelled` instead of their result.
+ * Deletes and batch writes that already committed complete normally.
  */
 
 const ZosStorage = {
@@ -56,6 +63,13 @@ const ZosStorage = {
   /** @type {Map<string, Uint8Array>} In-memory content cache for synchronous reads */
   contentCache: new Map(),
 
+  // === Cancellation ===
+  /** @type {Set<number>} Request IDs cancelled while in flight */
+  cancelled: new Set(),
+
+  /** @type {Map<number, IDBTransaction>} Open content transactions by request ID */
+  transactions: new Map(),
+
   // === Supervisor Reference ===
   /** @type {object|null} Reference to the WASM supervisor for callbacks */
   supervisor: null,
@@ -423,9 +437,10 @@ const ZosStorage = {
    * Store file content.
    * @param {string} path - The file path
    * @param {Uint8Array} data - The content bytes
+   * @param {number} [requestId] - HAL request ID, so cancelRequest can abort the write
    * @returns {Promise<boolean>} True if successful
    */
-  async putContent(path, data) {
+  async putContent(path, data, requestId) {
     if (!this.db) {
       throw new Error('ZosStorage not initialized');
     }
@@ -436,13 +451,18 @@ const ZosStorage = {
     return new Promise((resolve, reject) => {
       const tx = this.db.transaction([this.CONTENT_STORE], 'readwrite');
       const store = tx.objectStore(this.CONTENT_STORE);
+      this.trackTransaction(requestId, tx);
 
       // Store as object with path key
       const record = { path, data };
       const request = store.put(record);
 
-      request.onsuccess = () => resolve(true);
+      request.onsuccess = () => {
+        this.untrackTransaction(requestId);
+        resolve(true);
+      };
       request.onerror = (event) => {
+        this.untrackTransaction(requestId);
         console.error('[ZosStorage] putContent failed:', event.target.error);
         // Revert cache on failure
         this.contentCache.delete(path);
@@ -454,9 +474,10 @@ const ZosStorage = {
   /**
    * Get file content.
    * @param {string} path - The file path
+   * @param {number} [requestId] - HAL request ID, so cancelRequest can abort the read
    * @returns {Promise<Uint8Array|null>} The content or null if not found
    */
-  async getContent(path) {
+  async getContent(path, requestId) {
     if (!this.db) {
       throw new Error('ZosStorage not initialized');
     }
@@ -464,13 +485,16 @@ const ZosStorage = {
     return new Promise((resolve, reject) => {
       const tx = this.db.transaction([this.CONTENT_STORE], 'readonly');
       const store = tx.objectStore(this.CONTENT_STORE);
+      this.trackTransaction(requestId, tx);
       const request = store.get(path);
 
       request.onsuccess = () => {
+        this.untrackTransaction(requestId);
         const result = request.result;
         resolve(result ? result.data : null);
       };
       request.onerror = (event) => {
+        this.untrackTransaction(requestId);
         console.error('[ZosStorage] getContent failed:', event.target.error);
         reject(event.target.error);
       };
@@ -633,6 +657,73 @@ const ZosStorage = {
     });
   },
 
+  // ==========================================================================
+  // Cancellation
+  // ==========================================================================
+
+  /**
+   * Remember the transaction serving a HAL request.
+   * @param {number|undefined} requestId - HAL request ID (none for direct calls)
+   * @param {IDBTransaction} tx - The open transaction
+   */
+  trackTransaction(requestId, tx) {
+    if (requestId === undefined) {
+      return;
+    }
+    if (this.cancelled.has(requestId)) {
+      tx.abort();
+      return;
+    }
+    this.transactions.set(requestId, tx);
+  },
+
+  /**
+   * Forget the transaction of a HAL request once it has finished.
+   * @param {number|undefined} requestId - HAL request ID
+   */
+  untrackTransaction(requestId) {
+    if (requestId !== undefined) {
+      this.transactions.delete(requestId);
+    }
+  },
+
+  /**
+   * Cancel an in-flight request.
+   * Called by HAL on SYS_STORAGE_CANCEL. The request still completes through
+   * its start* method, reporting cancelled where the work could be stopped.
+   * @param {number} requestId - Request ID to cancel
+   */
+  cancelRequest(requestId) {
+    console.log(`[ZosStorage] cancelRequest: request_id=${requestId}`);
+    this.cancelled.add(requestId);
+
+    const tx = this.transactions.get(requestId);
+    if (tx) {
+      this.transactions.delete(requestId);
+      try {
+        tx.abort();
+      } catch (e) {
+        // Already committed - the request completes with its real result
+        console.log(`[ZosStorage] cancelRequest: request_id=${requestId} already finished`);
+      }
+    }
+  },
+
+  /**
+   * Report a request as cancelled if cancelRequest was called for it.
+   * Always clears the cancellation mark.
+   * @param {object} supervisor - Supervisor captured by the start* method
+   * @param {number} requestId - Request ID
+   * @returns {boolean} True if the cancellation was reported
+   */
+  finishIfCancelled(supervisor, requestId) {
+    if (!this.cancelled.delete(requestId)) {
+      return false;
+    }
+    this.safeSupervisorCallback(() => supervisor.notify_storage_cancelled(requestId));
+    return true;
+  },
+
   // ==========================================================================
   // Supervisor Async API (HAL callbacks)
   // These methods are called by HAL and notify the supervisor when complete.
@@ -662,7 +753,7 @@ const ZosStorage = {
       let data = null;
       if (key.startsWith('content:')) {
         const path = key.substring(8); // Remove 'content:' prefix
-        const content = await this.getContent(path);
+        const content = await this.getContent(path, requestId);
         if (content) {
           data = content;
         }
@@ -680,6 +771,10 @@ const ZosStorage = {
         }
       }
 
+      if (this.finishIfCancelled(supervisor, requestId)) {
+        return;
+      }
+
       // Use safeSupervisorCallback to avoid re-entrancy with wasm-bindgen's RefCell borrow
       if (data) {
         this.safeSupervisorCallback(() => supervisor.notify_storage_read_complete(requestId, data));
@@ -687,6 +782,9 @@ const ZosStorage = {
         this.safeSupervisorCallback(() => supervisor.notify_storage_not_found(requestId));
       }
     } catch (e) {
+      if (this.finishIfCancelled(supervisor, requestId)) {
+        return;
+      }
       console.error(`[ZosStorage] startRead error: ${e.message}`);
       this.safeSupervisorCallback(() => supervisor.notify_storage_error(requestId, e.message));
     }
@@ -727,7 +825,7 @@ const ZosStorage = {
 
       if (key.startsWith('content:')) {
         const path = key.substring(8);
-        await this.putContent(path, value);
+        await this.putContent(path, value, requestId);
       } else if (key.startsWith('inode:')) {
         const path = key.substring(6);
         const inodeJson = new TextDecoder().decode(value);
@@ -743,8 +841,13 @@ const ZosStorage = {
       // Use safeSupervisorCallback to avoid re-entrancy with wasm-bindgen's RefCell borrow.
       // The supervisor may still be borrowed from the call that initiated this
       // storage operation, so we queue the callback to ensure it happens safely.
+      // A write that landed despite a cancel still reports success.
+      this.cancelled.delete(requestId);
       this.safeSupervisorCallback(() => supervisor.notify_storage_write_complete(requestId));
     } catch (e) {
+      if (this.finishIfCancelled(supervisor, requestId)) {
+        return;
+      }
       console.error(`[ZosStorage] startWrite error: ${e.message}`);
       this.safeSupervisorCallback(() => supervisor.notify_storage_error(requestId, e.message));
     }
@@ -781,8 +884,10 @@ const ZosStorage = {
       }
 
       // Use safeSupervisorCallback to avoid re-entrancy with wasm-bindgen's RefCell borrow
+      this.cancelled.delete(requestId);
       this.safeSupervisorCallback(() => supervisor.notify_storage_write_complete(requestId));
     } catch (e) {
+      this.cancelled.delete(requestId);
       console.error(`[ZosStorage] startDelete error: ${e.message}`);
       this.safeSupervisorCallback(() => supervisor.notify_storage_error(requestId, e.message));
     }
@@ -818,9 +923,16 @@ const ZosStorage = {
       const keys = children.map((inode) => inode.path);
       const keysJson = JSON.stringify(keys);
 
+      if (this.finishIfCancelled(supervisor, requestId)) {
+        return;
+      }
+
       // Use safeSupervisorCallback to avoid re-entrancy with wasm-bindgen's RefCell borrow
       this.safeSupervisorCallback(() => supervisor.notify_storage_list_complete(requestId, keysJson));
     } catch (e) {
+      if (this.finishIfCancelled(supervisor, requestId)) {
+        return;
+      }
       console.error(`[ZosStorage] startList error: ${e.message}`);
       this.safeSupervisorCallback(() => supervisor.notify_storage_error(requestId, e.message));
     }
@@ -899,8 +1011,10 @@ const ZosStorage = {
       });
 
       // Use safeSupervisorCallback to avoid re-entrancy with wasm-bindgen's RefCell borrow
+      this.cancelled.delete(requestId);
       this.safeSupervisorCallback(() => supervisor.notify_storage_write_complete(requestId));
     } catch (e) {
+      this.cancelled.delete(requestId);
       console.error(`[ZosStorage] startBatchWrite error: ${e.message}`);
       this.safeSupervisorCallback(() => supervisor.notify_storage_error(requestId, e.message));
     }
@@ -932,7 +1046,7 @@ const ZosStorage = {
         const path = key.substring(8);
         exists = this.contentCache.has(path);
         if (!exists) {
-          const content = await this.getContent(path);
+          const content = await this.getContent(path, requestId);
           exists = content !== null;
         }
       } else if (key.startsWith('inode:')) {
@@ -952,9 +1066,16 @@ const ZosStorage = {
         }
       }
 
+      if (this.finishIfCancelled(supervisor, requestId)) {
+        return;
+      }
+
       // Use safeSupervisorCallback to avoid re-entrancy with wasm-bindgen's RefCell borrow
       this.safeSupervisorCallback(() => supervisor.notify_storage_exists_complete(requestId, exists));
     } catch (e) {
+      if (this.finishIfCancelled(supervisor, requestId)) {
+        return;
+      }
       console.error(`[ZosStorage] startExists error: ${e.message}`);
       this.safeSupervisorCallback(() => supervisor.notify_storage_error(requestId, e.message));
     }
@@ -74,9 +74,11 @@ vi.mock('lucide-react', () => ({
   Shield: () => <span data-testid="shield-icon">Shield</span>,
   Palette: () => <span data-testid="palette-icon">Palette</span>,
   Network: () => <span data-testid="network-icon">Network</span>,
+  Clipboard: () => <span data-testid="clipboard-icon">Clipboard</span>,
   Brain: () => <span data-testid="brain-icon">Brain</span>,
   Cpu: () => <span data-testid="cpu-icon">Cpu</span>,
   Users: () => <span data-testid="users-icon">Users</span>,
+  Rocket: () => <span data-testid="rocket-icon">Rocket</span>,
 }));
 
 // Mock panel components
@@ -108,10 +110,18 @@ vi.mock('./panels/ThemePanel', () => ({
   ThemePanel: () => <div data-testid="theme-panel">Theme Panel</div>,
 }));
 
+vi.mock('./panels/StartupPanel', () => ({
+  StartupPanel: () => <div data-testid="startup-panel">Startup Panel</div>,
+}));
+
 vi.mock('./panels/NetworkPanel', () => ({
   NetworkPanel: () => <div data-testid="network-panel">Network Panel</div>,
 }));
 
+vi.mock('./panels/ClipboardPanel', () => ({
+  ClipboardPanel: () => <div data-testid="clipboard-panel">Clipboard Panel</div>,
+}));
+
 vi.mock('./panels/NeuralKeyPanel', () => ({
   NeuralKeyPanel: () => <div data-testid="neural-key-panel">Neural Key Panel</div>,
 }));
@@ -145,10 +155,12 @@ const mockStore = {
   timeFormat24h: false,
   timezone: 'UTC',
   rpcEndpoint: 'http://localhost:8545',
+  networkOffline: false,
   pendingNavigation: null,
   setTimeFormat24h: vi.fn(),
   setTimezone: vi.fn(),
   setRpcEndpoint: vi.fn(),
+  setNetworkOffline: vi.fn(),
   setPendingNavigation: vi.fn(),
   clearPendingNavigation: vi.fn(),
   loadIdentityPreferences: vi.fn(),
@@ -165,6 +177,7 @@ vi.mock('../../stores', () => ({
   selectTimeFormat24h: (state: typeof mockStore) => state.timeFormat24h,
   selectTimezone: (state: typeof mockStore) => state.timezone,
   selectRpcEndpoint: (state: typeof mockStore) => state.rpcEndpoint,
+  selectNetworkOffline: (state: typeof mockStore) => state.networkOffline,
   selectPendingNavigation: (state: typeof mockStore) => state.pendingNavigation,
   useIdentityStore: () => ({ currentUser: null }),
   selectCurrentUser: () => null,
@@ -207,7 +220,9 @@ describe('SettingsApp', () => {
       expect(screen.getByTestId('nav-general')).toBeDefined();
       expect(screen.getByTestId('nav-identity')).toBeDefined();
       expect(screen.getByTestId('nav-network')).toBeDefined();
+      expect(screen.getByTestId('nav-clipboard')).toBeDefined();
       expect(screen.getByTestId('nav-permissions')).toBeDefined();
+      expect(screen.getByTestId('nav-startup')).toBeDefined();
       expect(screen.getByTestId('nav-theme')).toBeDefined();
 
       // Identity sub-items
@@ -263,6 +278,17 @@ describe('SettingsApp', () => {
       expect(screen.getByTestId('theme-panel')).toBeDefined();
     });
 
+    it('switches to startup panel when clicked', async () => {
+      render(<SettingsApp />);
+
+      const startupNav = screen.getByTestId('nav-startup');
+      await act(async () => {
+        fireEvent.click(startupNav);
+      });
+
+      expect(screen.getByTestId('startup-panel')).toBeDefined();
+    });
+
     it('switches to network panel when clicked', async () => {
       render(<SettingsApp />);
 
@@ -274,6 +300,17 @@ describe('SettingsApp', () => {
       expect(screen.getByTestId('network-panel')).toBeDefined();
     });
 
+    it('switches to clipboard panel when clicked', async () => {
+      render(<SettingsApp />);
+
+      const clipboardNav = screen.getByTestId('nav-clipboard');
+      await act(async () => {
+        fireEvent.click(clipboardNav);
+      });
+
+      expect(screen.getByTestId('clipboard-panel')).toBeDefined();
+    });
+
     it('drills directly to neural key panel from explorer', async () => {
       render(<SettingsApp />);
 
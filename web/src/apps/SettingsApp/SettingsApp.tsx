@@ -7,12 +7,25 @@ import {
   type PanelDrillItem,
   ButtonPlus,
 } from '@cypher-asi/zui';
-import { Clock, User, Shield, Palette, Network, Brain, Cpu, Users } from 'lucide-react';
+import {
+  Clock,
+  User,
+  Shield,
+  Palette,
+  Network,
+  Clipboard,
+  Brain,
+  Cpu,
+  Users,
+  Rocket,
+} from 'lucide-react';
 import { GeneralPanel } from './panels/GeneralPanel';
 import { IdentitySettingsPanel } from './panels/IdentitySettingsPanel';
 import { PermissionsPanel } from './panels/PermissionsPanel';
 import { ThemePanel } from './panels/ThemePanel';
+import { StartupPanel } from './panels/StartupPanel';
 import { NetworkPanel } from './panels/NetworkPanel';
+import { ClipboardPanel } from './panels/ClipboardPanel';
 import { NeuralKeyPanel } from './panels/NeuralKeyPanel';
 import { MachineKeysPanel } from './panels/MachineKeysPanel';
 import { LinkedAccountsPanel } from './panels/LinkedAccountsPanel';
@@ -23,6 +36,7 @@ import {
   selectTimeFormat24h,
   selectTimezone,
   selectRpcEndpoint,
+  selectNetworkOffline,
   selectPendingNavigation,
   type SettingsArea,
   type SettingsSubPanel,
@@ -35,7 +49,9 @@ const AREA_LABELS: Record<SettingsArea, string> = {
   general: 'Time',
   identity: 'Identity',
   network: 'Network',
+  clipboard: 'Clipboard',
   permissions: 'Permissions',
+  startup: 'Startup',
   theme: 'Theme',
 };
 
@@ -70,6 +86,8 @@ export function SettingsApp() {
   // Network settings
   const rpcEndpoint = useSettingsStore(selectRpcEndpoint);
   const setRpcEndpoint = useSettingsStore((state) => state.setRpcEndpoint);
+  const networkOffline = useSettingsStore(selectNetworkOffline);
+  const setNetworkOffline = useSettingsStore((state) => state.setNetworkOffline);
 
   // Navigation state from store (replaces module-level pendingNavigation)
   const pendingNavigation = useSettingsStore(selectPendingNavigation);
@@ -106,15 +124,35 @@ export function SettingsApp() {
           // pushPanelWithSelectionRef also updates Explorer selection for sub-panels
           return <IdentitySettingsPanel onDrillDown={(item) => pushPanelWithSelectionRef.current(item)} />;
         case 'network':
-          return <NetworkPanel rpcEndpoint={rpcEndpoint} onRpcEndpointChange={setRpcEndpoint} />;
+          return (
+            <NetworkPanel
+              rpcEndpoint={rpcEndpoint}
+              onRpcEndpointChange={setRpcEndpoint}
+              offline={networkOffline}
+              onOfflineChange={setNetworkOffline}
+            />
+          );
+        case 'clipboard':
+          return <ClipboardPanel />;
         case 'permissions':
           // Use ref to avoid recreating when pushPanel changes
           return <PermissionsPanel onDrillDown={(item) => pushPanelRef.current(item)} />;
+        case 'startup':
+          return <StartupPanel />;
         case 'theme':
           return <ThemePanel />;
       }
     },
-    [timeFormat24h, timezone, setTimeFormat24h, setTimezone, rpcEndpoint, setRpcEndpoint]
+    [
+      timeFormat24h,
+      timezone,
+      setTimeFormat24h,
+      setTimezone,
+      rpcEndpoint,
+      setRpcEndpoint,
+      networkOffline,
+      setNetworkOffline,
+    ]
   );
 
   // Helper to create PanelDrillItem for a given sub-panel (for deep-linking)
@@ -180,6 +218,11 @@ export function SettingsApp() {
         label: 'Network',
         icon: <Network size={14} />,
       },
+      {
+        id: 'clipboard',
+        label: 'Clipboard',
+        icon: <Clipboard size={14} />,
+      },
       {
         id: 'general',
         label: 'Time',
@@ -190,6 +233,11 @@ export function SettingsApp() {
         label: 'Permissions',
         icon: <Shield size={14} />,
       },
+      {
+        id: 'startup',
+        label: 'Startup',
+        icon: <Rocket size={14} />,
+      },
       {
         id: 'theme',
         label: 'Theme',
@@ -266,7 +314,7 @@ export function SettingsApp() {
       }
 
       // Top-level area selection
-      if (['identity', 'network', 'general', 'permissions', 'theme'].includes(id)) {
+      if (['identity', 'network', 'clipboard', 'general', 'permissions', 'startup', 'theme'].includes(id)) {
         handleAreaSelect(id);
       }
     },
@@ -283,7 +331,7 @@ export function SettingsApp() {
         if (newStack.length > 0) {
           const targetId = newStack[newStack.length - 1].id;
           // If it's a top-level area, select it
-          if (['identity', 'network', 'general', 'permissions', 'theme'].includes(targetId)) {
+          if (['identity', 'network', 'clipboard', 'general', 'permissions', 'startup', 'theme'].includes(targetId)) {
             setSelectedExplorerId(targetId);
           }
         }
@@ -0,0 +1,121 @@
+import { useState, useEffect, useMemo, useCallback } from 'react';
+import { GroupCollapsible, Label, Menu, Text, type MenuItem } from '@cypher-asi/zui';
+import { Clipboard, ClipboardX } from 'lucide-react';
+import { useSupervisor } from '@desktop/hooks/useSupervisor';
+import {
+  ClipboardServiceClient,
+  DEFAULT_CLIPBOARD_SYNC,
+  type ClipboardSyncSettings,
+} from '@/client-services';
+import styles from './ClipboardPanel.module.css';
+
+const syncItems: MenuItem[] = [
+  { id: 'on', label: 'Sync with my machines', icon: <Clipboard size={14} /> },
+  { id: 'off', label: 'Keep on this machine', icon: <ClipboardX size={14} /> },
+];
+
+/**
+ * Clipboard Settings Panel
+ * - Per-machine clipboard sync toggle
+ * - What sync leaves out
+ */
+export function ClipboardPanel() {
+  const supervisor = useSupervisor();
+  const client = useMemo(
+    () => (supervisor ? new ClipboardServiceClient(supervisor) : null),
+    [supervisor]
+  );
+  const [settings, setSettings] = useState<ClipboardSyncSettings>(DEFAULT_CLIPBOARD_SYNC);
+  const [loaded, setLoaded] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!client) return;
+    let stale = false;
+    client
+      .getSync()
+      .then((current) => {
+        if (stale) return;
+        setSettings(current);
+        setLoaded(true);
+      })
+      .catch((err) => {
+        if (!stale) setError(err instanceof Error ? err.message : String(err));
+      });
+    return () => {
+      stale = true;
+    };
+  }, [client]);
+
+  const handleSyncChange = useCallback(
+    (id: string) => {
+      // Not until the current settings are known, so nothing is overwritten
+      if (!client || !loaded) return;
+      setError(null);
+      client
+        .setSync({ enabled: id === 'on' })
+        .then(setSettings)
+        .catch((err) => setError(err instanceof Error ? err.message : String(err)));
+    },
+    [client, loaded]
+  );
+
+  return (
+    <div className={styles.panelContainer}>
+      <GroupCollapsible title="Sync" defaultOpen className={styles.collapsibleSection}>
+        <div className={styles.fieldContent}>
+          <Menu
+            items={syncItems}
+            value={settings.enabled ? 'on' : 'off'}
+            onChange={handleSyncChange}
+            background="none"
+            border="none"
+          />
+          <Text size="xs" variant="muted">
+            Sync is set on each machine and is off until you turn it on. Synced entries are
+            encrypted for your other machines and only applied where sync is on too.
+          </Text>
+          {error && (
+            <Label size="xs" variant="warning">
+              {error}
+            </Label>
+          )}
+        </div>
+      </GroupCollapsible>
+
+      <GroupCollapsible title="Never synced" defaultOpen className={styles.collapsibleSection}>
+        <div className={styles.fieldContent}>
+          <ul className={styles.ruleList}>
+            <li>
+              <Text size="xs" variant="muted">
+                Entries larger than {settings.max_bytes} bytes
+              </Text>
+            </li>
+            <li>
+              <Text size="xs" variant="muted">
+                Entries an app marks sensitive, such as passwords
+              </Text>
+            </li>
+            {settings.excluded_sources.length > 0 && (
+              <li>
+                <Text size="xs" variant="muted">
+                  Entries copied in {settings.excluded_sources.join(', ')}
+                </Text>
+              </li>
+            )}
+            <li>
+              <Text size="xs" variant="muted">
+                Text that looks like a key or access token
+              </Text>
+            </li>
+            <li>
+              <Text size="xs" variant="muted">
+                Entries that came from another machine
+              </Text>
+            </li>
+          </ul>
+        </div>
+      </GroupCollapsible>
+    </div>
+  );
+}
@@ -0,0 +1 @@
+export { ClipboardPanel } from './ClipboardPanel';
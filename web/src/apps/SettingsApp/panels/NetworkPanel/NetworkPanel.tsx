@@ -1,19 +1,32 @@
 import { useState, useCallback } from 'react';
-import { GroupCollapsible, Input, Button, Label, Text } from '@cypher-asi/zui';
-import { Check, RotateCcw } from 'lucide-react';
+import { GroupCollapsible, Input, Button, Label, Menu, Text, type MenuItem } from '@cypher-asi/zui';
+import { Check, RotateCcw, Wifi, WifiOff } from 'lucide-react';
 import { DEFAULT_RPC_ENDPOINT } from '@/stores';
 import styles from './NetworkPanel.module.css';
 
 interface NetworkPanelProps {
   rpcEndpoint: string;
   onRpcEndpointChange: (endpoint: string) => void;
+  offline: boolean;
+  onOfflineChange: (offline: boolean) => void;
 }
 
+const connectivityItems: MenuItem[] = [
+  { id: 'online', label: 'Online', icon: <Wifi size={14} /> },
+  { id: 'offline', label: 'Offline', icon: <WifiOff size={14} /> },
+];
+
 /**
  * Network Settings Panel
+ * - System-wide offline mode
  * - ZERO-ID RPC endpoint configuration
  */
-export function NetworkPanel({ rpcEndpoint, onRpcEndpointChange }: NetworkPanelProps) {
+export function NetworkPanel({
+  rpcEndpoint,
+  onRpcEndpointChange,
+  offline,
+  onOfflineChange,
+}: NetworkPanelProps) {
   const [editValue, setEditValue] = useState(rpcEndpoint);
   const [isDirty, setIsDirty] = useState(false);
 
@@ -47,6 +60,21 @@ export function NetworkPanel({ rpcEndpoint, onRpcEndpointChange }: NetworkPanelP
 
   return (
     <div className={styles.panelContainer}>
+      <GroupCollapsible title="Connectivity" defaultOpen className={styles.collapsibleSection}>
+        <div className={styles.fieldContent}>
+          <Menu
+            items={connectivityItems}
+            value={offline ? 'offline' : 'online'}
+            onChange={(id: string) => onOfflineChange(id === 'offline')}
+            background="none"
+            border="none"
+          />
+          <Text size="xs" variant="muted">
+            While offline, every request made through the network service fails.
+          </Text>
+        </div>
+      </GroupCollapsible>
+
       <GroupCollapsible title="Zero-ID RPC" defaultOpen className={styles.collapsibleSection}>
         <div className={styles.fieldContent}>
           <Text size="xs" variant="muted" className={styles.fieldLabel}>
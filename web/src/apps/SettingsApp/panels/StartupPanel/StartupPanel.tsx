@@ -0,0 +1,144 @@
+import { useCallback, useMemo } from 'react';
+import { GroupCollapsible, Input, Button, Menu, Text, type MenuItem } from '@cypher-asi/zui';
+import { Power, PowerOff, Trash2 } from 'lucide-react';
+import { useSettingsStore, selectLoginItems } from '@/stores';
+import { MAX_LOGIN_ITEMS, MAX_LOGIN_ITEM_DELAY_MS, type LoginItem } from '@/client-services';
+import { useIdentityServiceClient } from '@desktop/hooks/useIdentityServiceClient';
+import styles from './StartupPanel.module.css';
+
+// Apps that can be opened at login (same ids as the Begin menu)
+const LAUNCHABLE_APPS: Record<string, string> = {
+  terminal: 'Terminal',
+  files: 'Files',
+  calculator: 'Calculator',
+  clock: 'Clock',
+  settings: 'Settings',
+};
+
+/**
+ * Startup Settings Panel
+ * - Apps opened automatically after login, in order
+ * - Per-item enable/disable and delay
+ *
+ * The list is stored in the identity preferences; every change replaces it.
+ */
+export function StartupPanel() {
+  const loginItems = useSettingsStore(selectLoginItems);
+  const setLoginItems = useSettingsStore((state) => state.setLoginItems);
+  const { userId } = useIdentityServiceClient();
+
+  const save = useCallback(
+    (items: LoginItem[]) => {
+      if (userId === null) return;
+      setLoginItems(userId, items).catch(() => {
+        // Store reverts the list; error already logged
+      });
+    },
+    [userId, setLoginItems]
+  );
+
+  const updateItem = useCallback(
+    (appId: string, change: Partial<LoginItem>) => {
+      save(loginItems.map((item) => (item.app_id === appId ? { ...item, ...change } : item)));
+    },
+    [loginItems, save]
+  );
+
+  const handleDelayChange = useCallback(
+    (appId: string, e: React.ChangeEvent<HTMLInputElement>) => {
+      const seconds = Number(e.target.value);
+      if (!Number.isFinite(seconds) || seconds < 0) return;
+      const delayMs = Math.min(Math.round(seconds * 1000), MAX_LOGIN_ITEM_DELAY_MS);
+      updateItem(appId, { delay_ms: delayMs });
+    },
+    [updateItem]
+  );
+
+  const handleRemove = useCallback(
+    (appId: string) => {
+      save(loginItems.filter((item) => item.app_id !== appId));
+    },
+    [loginItems, save]
+  );
+
+  const handleAdd = useCallback(
+    (appId: string) => {
+      save([...loginItems, { app_id: appId, enabled: true, delay_ms: 0 }]);
+    },
+    [loginItems, save]
+  );
+
+  // Apps not in the list yet
+  const addItems: MenuItem[] = useMemo(
+    () =>
+      Object.entries(LAUNCHABLE_APPS)
+        .filter(([id]) => !loginItems.some((item) => item.app_id === id))
+        .map(([id, label]) => ({ id, label })),
+    [loginItems]
+  );
+
+  return (
+    <div className={styles.panelContainer}>
+      <GroupCollapsible title="Login Items" defaultOpen className={styles.collapsibleSection}>
+        <div className={styles.fieldContent}>
+          {loginItems.length === 0 && (
+            <Text size="xs" variant="muted">
+              No apps open at login.
+            </Text>
+          )}
+
+          {loginItems.map((item) => (
+            <div
+              key={item.app_id}
+              className={`${styles.itemRow} ${item.enabled ? '' : styles.itemDisabled}`}
+            >
+              <Text size="sm" className={styles.itemName}>
+                {LAUNCHABLE_APPS[item.app_id] ?? item.app_id}
+              </Text>
+              <Input
+                type="number"
+                min={0}
+                max={MAX_LOGIN_ITEM_DELAY_MS / 1000}
+                value={String(item.delay_ms / 1000)}
+                onChange={(e: React.ChangeEvent<HTMLInputElement>) =>
+                  handleDelayChange(item.app_id, e)
+                }
+                className={styles.delayInput}
+                title="Delay (seconds)"
+              />
+              <Button
+                variant="ghost"
+                size="sm"
+                onClick={() => updateItem(item.app_id, { enabled: !item.enabled })}
+                title={item.enabled ? 'Disable' : 'Enable'}
+              >
+                {item.enabled ? <Power size={14} /> : <PowerOff size={14} />}
+              </Button>
+              <Button
+                variant="ghost"
+                size="sm"
+                onClick={() => handleRemove(item.app_id)}
+                title="Remove"
+              >
+                <Trash2 size={14} />
+              </Button>
+            </div>
+          ))}
+
+          <Text size="xs" variant="muted">
+            Apps open in this order once the system services are ready, each after its delay
+            in seconds.
+          </Text>
+        </div>
+      </GroupCollapsible>
+
+      {addItems.length > 0 && loginItems.length < MAX_LOGIN_ITEMS && (
+        <GroupCollapsible title="Add App" defaultOpen className={styles.collapsibleSection}>
+          <div className={styles.fieldContent}>
+            <Menu items={addItems} onChange={handleAdd} background="none" border="none" />
+          </div>
+        </GroupCollapsible>
+      )}
+    </div>
+  );
+}
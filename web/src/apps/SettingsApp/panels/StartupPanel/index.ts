@@ -0,0 +1 @@
+export { StartupPanel } from './StartupPanel';
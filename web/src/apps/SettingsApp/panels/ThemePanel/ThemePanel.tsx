@@ -8,8 +8,9 @@ import {
   type AccentColor,
   type MenuItem,
 } from '@cypher-asi/zui';
-import { Sun, Moon, Monitor, Image } from 'lucide-react';
+import { Sun, Moon, Monitor, Image, Contrast, Eye, Activity } from 'lucide-react';
 import { useBackground } from '@desktop/Desktop';
+import { useSettingsStore, selectDisplaySettings, type ColorFilter } from '@/stores';
 import styles from './ThemePanel.module.css';
 
 // Human-readable labels for themes
@@ -39,6 +40,15 @@ const ACCENT_HEX: Record<AccentColor, string> = {
   rose: '#f43f5e',
 };
 
+// Human-readable labels for color filters
+const COLOR_FILTER_LABELS: Record<ColorFilter, string> = {
+  none: 'None',
+  protanopia: 'Red-weak (protanopia)',
+  deuteranopia: 'Green-weak (deuteranopia)',
+  tritanopia: 'Blue-weak (tritanopia)',
+  grayscale: 'Grayscale',
+};
+
 // Theme icons
 const THEME_ICONS: Record<Theme, typeof Moon> = {
   dark: Moon,
@@ -51,12 +61,15 @@ const THEME_ICONS: Record<Theme, typeof Moon> = {
  * - Theme mode (dark/light/system)
  * - Accent color selection
  * - Background pattern selection
+ * - Accessibility: high contrast, color filter, reduced motion
  *
  * Wired up to actual ZUI theme context and Desktop background context.
  */
 export function ThemePanel() {
   const { theme, accent, setTheme, setAccent } = useTheme();
   const backgroundCtx = useBackground();
+  const display = useSettingsStore(selectDisplaySettings);
+  const setDisplaySettings = useSettingsStore((state) => state.setDisplaySettings);
 
   const backgrounds = backgroundCtx?.backgrounds ?? [];
   const currentBackground = backgroundCtx?.getActiveBackground() ?? 'grain';
@@ -86,6 +99,25 @@ export function ThemePanel() {
     icon: <Image size={14} />,
   }));
 
+  // Accessibility menu items
+  const contrastItems: MenuItem[] = [
+    { id: 'standard', label: 'Standard contrast', icon: <Contrast size={14} /> },
+    { id: 'high', label: 'High contrast', icon: <Contrast size={14} /> },
+  ];
+
+  const colorFilterItems: MenuItem[] = (Object.keys(COLOR_FILTER_LABELS) as ColorFilter[]).map(
+    (filter) => ({
+      id: filter,
+      label: COLOR_FILTER_LABELS[filter],
+      icon: <Eye size={14} />,
+    })
+  );
+
+  const motionItems: MenuItem[] = [
+    { id: 'full', label: 'Full motion', icon: <Activity size={14} /> },
+    { id: 'reduced', label: 'Reduced motion', icon: <Activity size={14} /> },
+  ];
+
   return (
     <div className={styles.panelContainer}>
       <GroupCollapsible
@@ -138,6 +170,37 @@ export function ThemePanel() {
           />
         </div>
       </GroupCollapsible>
+
+      <GroupCollapsible
+        title="Accessibility"
+        count={contrastItems.length + colorFilterItems.length + motionItems.length}
+        defaultOpen
+        className={styles.collapsibleSection}
+      >
+        <div className={styles.menuContent}>
+          <Menu
+            items={contrastItems}
+            value={display.high_contrast ? 'high' : 'standard'}
+            onChange={(id) => setDisplaySettings({ high_contrast: id === 'high' })}
+            background="none"
+            border="none"
+          />
+          <Menu
+            items={colorFilterItems}
+            value={display.color_filter}
+            onChange={(id) => setDisplaySettings({ color_filter: id as ColorFilter })}
+            background="none"
+            border="none"
+          />
+          <Menu
+            items={motionItems}
+            value={display.reduced_motion ? 'reduced' : 'full'}
+            onChange={(id) => setDisplaySettings({ reduced_motion: id === 'reduced' })}
+            background="none"
+            border="none"
+          />
+        </div>
+      </GroupCollapsible>
     </div>
   );
 }
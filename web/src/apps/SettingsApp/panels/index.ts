@@ -4,8 +4,10 @@ export { IdentitySettingsPanel } from './IdentitySettingsPanel';
 export { PermissionsPanel } from './PermissionsPanel';
 export { ProcessCapabilitiesPanel } from './ProcessCapabilitiesPanel';
 export { ThemePanel } from './ThemePanel';
+export { StartupPanel } from './StartupPanel';
 export { NeuralKeyPanel } from './NeuralKeyPanel';
 export { MachineKeysPanel } from './MachineKeysPanel';
 export { GenerateMachineKeyPanel } from './GenerateMachineKeyPanel';
 export { LinkedAccountsPanel } from './LinkedAccountsPanel';
 export { NetworkPanel, DEFAULT_RPC_ENDPOINT } from './NetworkPanel';
+export { ClipboardPanel } from './ClipboardPanel';
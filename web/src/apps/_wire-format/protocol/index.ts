@@ -15,6 +15,7 @@ export {
   MSG_UI_READY,
   MSG_APP_FOCUS,
   MSG_APP_ERROR,
+  MSG_APP_WINDOW_PROPERTIES,
   MSG_CAP_REVOKED,
   REVOKE_REASON_EXPLICIT,
   REVOKE_REASON_EXPIRED,
@@ -22,6 +23,7 @@ export {
   TYPE_CLOCK_STATE,
   TYPE_CALCULATOR_STATE,
   TYPE_SETTINGS_STATE,
+  TYPE_WINDOW_PROPERTIES,
   TYPE_BUTTON_PRESS,
   TYPE_TEXT_INPUT,
   TYPE_KEY_PRESS,
@@ -13,6 +13,7 @@ export const MSG_APP_INPUT = 0x2001;
 export const MSG_UI_READY = 0x2002;
 export const MSG_APP_FOCUS = 0x2003;
 export const MSG_APP_ERROR = 0x2004;
+export const MSG_APP_WINDOW_PROPERTIES = 0x2005;
 
 // Capability revocation notification (supervisor -> process)
 // Payload: [slot: u32, object_type: u8, object_id: u64, reason: u8]
@@ -27,6 +28,7 @@ export const REVOKE_REASON_PROCESS_EXIT = 3; // Source process exited
 export const TYPE_CLOCK_STATE = 0x01;
 export const TYPE_CALCULATOR_STATE = 0x02;
 export const TYPE_SETTINGS_STATE = 0x03;
+export const TYPE_WINDOW_PROPERTIES = 0x04;
 export const TYPE_BUTTON_PRESS = 0x10;
 export const TYPE_TEXT_INPUT = 0x11;
 export const TYPE_KEY_PRESS = 0x12;
@@ -0,0 +1,202 @@
+/**
+ * Clipboard Service IPC Client
+ *
+ * This TypeScript client provides a clean API for the clipboard service:
+ * copying and pasting text, listing recent entries, and reading or changing
+ * this machine's clipboard sync settings.
+ *
+ * Architecture:
+ * - Client constructs JSON IPC messages with proper message tags
+ * - Supervisor provides generic send_service_ipc() and callback registration
+ * - Synced entries travel between machines through the messaging service
+ */
+
+import { PendingRequestQueue } from '../shared/ipc';
+import type { MinimalSupervisor } from '../shared/types';
+
+// =============================================================================
+// Message Tags (mirrors zos-ipc clipboard module)
+// =============================================================================
+
+/** IPC message tags for clipboard service requests/responses */
+export const CLIPBOARD_MSG = {
+  /** Copy text to the clipboard */
+  COPY: 0x8e00,
+  /** Response with the entry and whether it is synced */
+  COPY_RESPONSE: 0x8e01,
+  /** Get the clipboard */
+  PASTE: 0x8e02,
+  /** Response with the current entry */
+  PASTE_RESPONSE: 0x8e03,
+  /** List recent entries */
+  HISTORY: 0x8e04,
+  /** Response with the entries, newest first */
+  HISTORY_RESPONSE: 0x8e05,
+  /** Get this machine's sync settings */
+  GET_SYNC: 0x8e06,
+  /** Response with the settings */
+  GET_SYNC_RESPONSE: 0x8e07,
+  /** Change this machine's sync settings */
+  SET_SYNC: 0x8e08,
+  /** Response once the settings are saved */
+  SET_SYNC_RESPONSE: 0x8e09,
+} as const;
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/** A copied piece of text */
+export interface ClipboardEntry {
+  id: number;
+  text: string;
+  /** App it was copied in, if known */
+  source?: string;
+  /** When it was copied, ms since the Unix epoch */
+  copied_at_ms: number;
+  /** Copied on another of the user's machines */
+  remote: boolean;
+  /** Marked sensitive by the app that copied it */
+  sensitive: boolean;
+}
+
+/** Why an entry wasn't synced */
+export type SyncExclusion =
+  | 'disabled'
+  | 'remote'
+  | 'too_large'
+  | 'marked_sensitive'
+  | 'excluded_source'
+  | 'looks_secret'
+  | 'stale';
+
+/** Result of a copy */
+export interface CopyResult {
+  id: number;
+  synced: boolean;
+  excluded?: SyncExclusion;
+}
+
+/** This machine's clipboard sync settings */
+export interface ClipboardSyncSettings {
+  /** Whether entries are synced with the user's other machines */
+  enabled: boolean;
+  /** Largest entry synced, in bytes */
+  max_bytes: number;
+  /** Apps whose entries are never synced */
+  excluded_sources: string[];
+}
+
+/** Default sync settings (mirrors SyncSettings::default) */
+export const DEFAULT_CLIPBOARD_SYNC: ClipboardSyncSettings = {
+  enabled: false,
+  max_bytes: 4 * 1024,
+  excluded_sources: [],
+};
+
+// =============================================================================
+// Error Classes
+// =============================================================================
+
+/**
+ * Base class for Clipboard Service errors.
+ */
+export class ClipboardServiceError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = 'ClipboardServiceError';
+    if (Error.captureStackTrace) {
+      Error.captureStackTrace(this, this.constructor);
+    }
+  }
+}
+
+// =============================================================================
+// Shared request queue for all ClipboardServiceClient instances
+// =============================================================================
+
+const requestQueue = new PendingRequestQueue({ name: 'ClipboardServiceClient' });
+
+// =============================================================================
+// ClipboardServiceClient
+// =============================================================================
+
+/**
+ * Client for Clipboard Service IPC.
+ */
+export class ClipboardServiceClient {
+  private supervisor: MinimalSupervisor;
+  private timeoutMs: number;
+
+  constructor(supervisor: MinimalSupervisor, timeoutMs = 5000) {
+    this.supervisor = supervisor;
+    this.timeoutMs = timeoutMs;
+    requestQueue.register(supervisor);
+  }
+
+  /**
+   * Send a request to the clipboard service and wait for its response.
+   */
+  private async request<T extends object>(tag: number, data: object): Promise<T> {
+    const tagHex = this.supervisor.send_service_ipc('clipboard', tag, JSON.stringify(data));
+    if (tagHex.startsWith('error:')) {
+      throw new ClipboardServiceError(tagHex);
+    }
+
+    const response = await requestQueue.addRequest<T | { error: string }>(
+      tagHex,
+      this.timeoutMs
+    );
+    if ('error' in response) {
+      throw new ClipboardServiceError((response as { error: string }).error);
+    }
+    return response as T;
+  }
+
+  // ===========================================================================
+  // Public API
+  // ===========================================================================
+
+  /**
+   * Copy text. Sensitive entries are never listed or synced.
+   */
+  async copy(
+    text: string,
+    options: { source?: string; sensitive?: boolean } = {}
+  ): Promise<CopyResult> {
+    return this.request<CopyResult>(CLIPBOARD_MSG.COPY, { text, ...options });
+  }
+
+  /**
+   * Get the clipboard, or null if nothing has been copied.
+   */
+  async paste(): Promise<ClipboardEntry | null> {
+    const response = await this.request<{ entry: ClipboardEntry | null }>(CLIPBOARD_MSG.PASTE, {});
+    return response.entry;
+  }
+
+  /**
+   * List recent entries, newest first.
+   */
+  async history(limit?: number): Promise<ClipboardEntry[]> {
+    const response = await this.request<{ entries: ClipboardEntry[] }>(
+      CLIPBOARD_MSG.HISTORY,
+      limit === undefined ? {} : { limit }
+    );
+    return response.entries;
+  }
+
+  /**
+   * Get this machine's sync settings.
+   */
+  async getSync(): Promise<ClipboardSyncSettings> {
+    return this.request<ClipboardSyncSettings>(CLIPBOARD_MSG.GET_SYNC, {});
+  }
+
+  /**
+   * Change this machine's sync settings; absent fields are kept.
+   */
+  async setSync(update: Partial<ClipboardSyncSettings>): Promise<ClipboardSyncSettings> {
+    return this.request<ClipboardSyncSettings>(CLIPBOARD_MSG.SET_SYNC, update);
+  }
+}
@@ -0,0 +1,134 @@
+/**
+ * Command Service IPC Client
+ *
+ * This TypeScript client provides a clean API for the command palette:
+ * searching the commands apps have registered with the command service and
+ * invoking one in the app that registered it.
+ *
+ * Architecture:
+ * - Client constructs JSON IPC messages with proper message tags
+ * - Supervisor provides generic send_service_ipc() and callback registration
+ * - The service delivers an invoked command to its app as MSG_COMMAND
+ */
+
+import { PendingRequestQueue } from '../shared/ipc';
+import type { MinimalSupervisor } from '../shared/types';
+
+// =============================================================================
+// Message Tags (mirrors zos-ipc commands module)
+// =============================================================================
+
+/** IPC message tags for command service requests/responses */
+export const COMMAND_MSG = {
+  /** Search registered commands */
+  QUERY: 0x8d04,
+  /** Response with ranked matches */
+  QUERY_RESPONSE: 0x8d05,
+  /** Run a command in the app that registered it */
+  INVOKE: 0x8d06,
+  /** Response once the command is delivered */
+  INVOKE_RESPONSE: 0x8d07,
+} as const;
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/** A registered command matching a palette query */
+export interface CommandMatch {
+  /** Process that registered the command */
+  pid: number;
+  /** App ID of that process (may be empty) */
+  app: string;
+  /** Command ID, unique within the process */
+  id: string;
+  /** Title shown in the palette */
+  title: string;
+  /** Match score; higher ranks first */
+  score: number;
+}
+
+// =============================================================================
+// Error Classes
+// =============================================================================
+
+/**
+ * Base class for Command Service errors.
+ */
+export class CommandServiceError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = 'CommandServiceError';
+    if (Error.captureStackTrace) {
+      Error.captureStackTrace(this, this.constructor);
+    }
+  }
+}
+
+// =============================================================================
+// Shared request queue for all CommandServiceClient instances
+// =============================================================================
+
+const requestQueue = new PendingRequestQueue({ name: 'CommandServiceClient' });
+
+// =============================================================================
+// CommandServiceClient
+// =============================================================================
+
+/**
+ * Client for Command Service IPC.
+ */
+export class CommandServiceClient {
+  private supervisor: MinimalSupervisor;
+  private timeoutMs: number;
+
+  constructor(supervisor: MinimalSupervisor, timeoutMs = 5000) {
+    this.supervisor = supervisor;
+    this.timeoutMs = timeoutMs;
+    requestQueue.register(supervisor);
+  }
+
+  /**
+   * Send a request to the command service and wait for its response.
+   */
+  private async request<T extends object>(tag: number, data: object): Promise<T> {
+    const tagHex = this.supervisor.send_service_ipc('commands', tag, JSON.stringify(data));
+    if (tagHex.startsWith('error:')) {
+      throw new CommandServiceError(tagHex);
+    }
+
+    const response = await requestQueue.addRequest<T | { error: string }>(
+      tagHex,
+      this.timeoutMs
+    );
+    if ('error' in response) {
+      throw new CommandServiceError((response as { error: string }).error);
+    }
+    return response as T;
+  }
+
+  // ===========================================================================
+  // Public API
+  // ===========================================================================
+
+  /**
+   * Search registered commands.
+   *
+   * Every whitespace-separated term must fuzzily match the title or a
+   * keyword. An empty query lists commands by title.
+   */
+  async query(query: string, limit?: number): Promise<CommandMatch[]> {
+    const response = await this.request<{ results: CommandMatch[] }>(
+      COMMAND_MSG.QUERY,
+      limit === undefined ? { query } : { query, limit }
+    );
+    return response.results;
+  }
+
+  /**
+   * Run the command `id` registered by `pid`.
+   */
+  async invoke(pid: number, id: string, args?: unknown): Promise<void> {
+    await this.request(COMMAND_MSG.INVOKE, args === undefined ? { pid, id } : { pid, id, args });
+  }
+}
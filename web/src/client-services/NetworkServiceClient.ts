@@ -0,0 +1,171 @@
+/**
+ * Network Service IPC Client
+ *
+ * This TypeScript client provides a clean API for reading and changing the
+ * network WASM process's system-wide policy (offline mode and the origin
+ * allow/deny lists) and for clearing its HTTP response cache.
+ *
+ * Architecture:
+ * - Client constructs JSON IPC messages with proper message tags
+ * - Supervisor provides generic send_service_ipc() and callback registration
+ * - The policy is persisted to /system/config/network.json by the service
+ *   before a change is acknowledged
+ */
+
+import { PendingRequestQueue } from '../shared/ipc';
+import type { MinimalSupervisor } from '../shared/types';
+
+// =============================================================================
+// Message Tags (mirrors zos-ipc net module)
+// =============================================================================
+
+/** IPC message tags for network service policy requests/responses */
+export const NETWORK_MSG = {
+  /** Request the current policy */
+  POLICY_GET: 0x9010,
+  /** Response with the policy */
+  POLICY_GET_RESPONSE: 0x9011,
+  /** Change the policy; absent fields are kept */
+  POLICY_SET: 0x9012,
+  /** Response with the policy once persisted */
+  POLICY_SET_RESPONSE: 0x9013,
+  /** Drop cached responses for one URL, or all of them */
+  CACHE_PURGE: 0x9016,
+  /** Response with the cache counters after the purge */
+  CACHE_PURGE_RESPONSE: 0x9017,
+} as const;
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/** System-wide network policy */
+export interface NetworkPolicy {
+  /** Fail every request with the Offline error */
+  offline: boolean;
+  /** Origins requests may go to; empty allows all that aren't denied */
+  allow: string[];
+  /** Origins requests may never go to */
+  deny: string[];
+}
+
+/** HTTP response cache counters */
+export interface NetworkCacheStats {
+  /** Responses cached */
+  entries: number;
+  /** Total size of cached bodies */
+  bytes: number;
+  /** Requests served without contacting the origin */
+  hits: number;
+  /** Cacheable requests sent to the origin */
+  misses: number;
+  /** Revalidations answered with 304 Not Modified */
+  revalidated: number;
+  /** Responses written to the cache */
+  stored: number;
+  /** Entries evicted to stay within the size limits */
+  evicted: number;
+}
+
+/** Default policy (online, no rules) */
+export const DEFAULT_NETWORK_POLICY: NetworkPolicy = {
+  offline: false,
+  allow: [],
+  deny: [],
+};
+
+// =============================================================================
+// Error Classes
+// =============================================================================
+
+/**
+ * Base class for Network Service errors.
+ */
+export class NetworkServiceError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = 'NetworkServiceError';
+    if (Error.captureStackTrace) {
+      Error.captureStackTrace(this, this.constructor);
+    }
+  }
+}
+
+// =============================================================================
+// Shared request queue for all NetworkServiceClient instances
+// =============================================================================
+
+const requestQueue = new PendingRequestQueue({ name: 'NetworkServiceClient' });
+
+// =============================================================================
+// NetworkServiceClient
+// =============================================================================
+
+/**
+ * Client for Network Service policy IPC.
+ */
+export class NetworkServiceClient {
+  private supervisor: MinimalSupervisor;
+  private timeoutMs: number;
+
+  constructor(supervisor: MinimalSupervisor, timeoutMs = 5000) {
+    this.supervisor = supervisor;
+    this.timeoutMs = timeoutMs;
+    requestQueue.register(supervisor);
+  }
+
+  /**
+   * Send a request to the network service and wait for its response.
+   */
+  private async request<T extends object>(tag: number, data: object): Promise<T> {
+    const tagHex = this.supervisor.send_service_ipc('network', tag, JSON.stringify(data));
+    if (tagHex.startsWith('error:')) {
+      throw new NetworkServiceError(tagHex);
+    }
+
+    const response = await requestQueue.addRequest<T | { error: string }>(
+      tagHex,
+      this.timeoutMs
+    );
+    if ('error' in response) {
+      throw new NetworkServiceError((response as { error: string }).error);
+    }
+    return response as T;
+  }
+
+  // ===========================================================================
+  // Public API
+  // ===========================================================================
+
+  /**
+   * Get the current network policy.
+   */
+  async getPolicy(): Promise<NetworkPolicy> {
+    return this.request<NetworkPolicy>(NETWORK_MSG.POLICY_GET, {});
+  }
+
+  /**
+   * Change part of the network policy.
+   *
+   * @returns The policy in effect once the change is persisted
+   */
+  async setPolicy(changes: Partial<NetworkPolicy>): Promise<NetworkPolicy> {
+    return this.request<NetworkPolicy>(NETWORK_MSG.POLICY_SET, changes);
+  }
+
+  /**
+   * Turn offline mode on or off.
+   */
+  async setOffline(offline: boolean): Promise<NetworkPolicy> {
+    return this.setPolicy({ offline });
+  }
+
+  /**
+   * Drop cached responses for `url`, or the whole cache if omitted.
+   *
+   * @returns The cache counters after the purge
+   */
+  async purgeCache(url?: string): Promise<NetworkCacheStats> {
+    return this.request<NetworkCacheStats>(NETWORK_MSG.CACHE_PURGE, url ? { url } : {});
+  }
+}
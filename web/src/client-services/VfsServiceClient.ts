@@ -0,0 +1,124 @@
+/**
+ * VFS Service IPC Client
+ *
+ * Writes to the VFS go through the VFS service (reads come straight from the
+ * ZosStorage cache via VfsStorageClient). This client covers what the
+ * desktop needs: creating directories and writing whole files.
+ *
+ * Architecture:
+ * - Client constructs JSON IPC messages with proper message tags
+ * - Supervisor provides generic send_service_ipc() and callback registration
+ * - Responses carry a serde `Result` (`{ Ok: ... }` or `{ Err: ... }`)
+ */
+
+import { PendingRequestQueue } from '../shared/ipc';
+import type { MinimalSupervisor } from '../shared/types';
+
+// =============================================================================
+// Message Tags (mirrors zos-ipc vfs_dir / vfs_file)
+// =============================================================================
+
+/** IPC message tags for VFS service requests */
+export const VFS_MSG = {
+  /** Create directory */
+  MKDIR: 0x8000,
+  /** Write file */
+  WRITE: 0x8010,
+} as const;
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/** Serde-encoded Rust `Result` */
+type VfsResult<T> = { Ok: T } | { Err: unknown };
+
+interface VfsResponse<T> {
+  result: VfsResult<T>;
+}
+
+// =============================================================================
+// Error Classes
+// =============================================================================
+
+/**
+ * Error reported by the VFS service (or failure to reach it).
+ */
+export class VfsServiceError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = 'VfsServiceError';
+    if (Error.captureStackTrace) {
+      Error.captureStackTrace(this, this.constructor);
+    }
+  }
+}
+
+// =============================================================================
+// Shared request queue for all VfsServiceClient instances
+// =============================================================================
+
+const requestQueue = new PendingRequestQueue({ name: 'VfsServiceClient' });
+
+// =============================================================================
+// VfsServiceClient
+// =============================================================================
+
+/**
+ * Client for VFS Service IPC communication.
+ */
+export class VfsServiceClient {
+  private supervisor: MinimalSupervisor;
+  private timeoutMs: number;
+
+  constructor(supervisor: MinimalSupervisor, timeoutMs = 5000) {
+    this.supervisor = supervisor;
+    this.timeoutMs = timeoutMs;
+    requestQueue.register(supervisor);
+  }
+
+  /**
+   * Send a request to the VFS service and unwrap its `Result`.
+   */
+  private async request<T>(tag: number, data: object): Promise<T> {
+    const tagHex = this.supervisor.send_service_ipc('vfs', tag, JSON.stringify(data));
+    if (tagHex.startsWith('error:')) {
+      throw new VfsServiceError(tagHex);
+    }
+
+    const response = await requestQueue.addRequest<VfsResponse<T>>(tagHex, this.timeoutMs);
+    if ('Err' in response.result) {
+      throw new VfsServiceError(`VFS error: ${JSON.stringify(response.result.Err)}`);
+    }
+    return response.result.Ok;
+  }
+
+  // ===========================================================================
+  // Public API
+  // ===========================================================================
+
+  /**
+   * Create a directory.
+   *
+   * @param path - Directory path
+   * @param createParents - Also create missing parent directories
+   */
+  async mkdir(path: string, createParents = true): Promise<void> {
+    await this.request<null>(VFS_MSG.MKDIR, { path, create_parents: createParents });
+  }
+
+  /**
+   * Write a whole file, replacing any existing content.
+   *
+   * @param path - File path
+   * @param content - Text (written as UTF-8) or raw bytes
+   */
+  async writeFile(path: string, content: string | Uint8Array): Promise<void> {
+    const bytes = typeof content === 'string' ? new TextEncoder().encode(content) : content;
+    await this.request<null>(VFS_MSG.WRITE, {
+      path,
+      content: Array.from(bytes),
+      encrypt: false,
+    });
+  }
+}
@@ -339,3 +339,10 @@ export function getCredentialsPath(userId: bigint | string | number): string {
 export function getZidSessionPath(userId: bigint | string | number): string {
   return `/home/${formatUserId(userId)}/.zos/identity/zid_session.json`;
 }
+
+/**
+ * Get the canonical VFS directory for a user's input recordings.
+ */
+export function getInputRecordingsDir(userId: bigint | string | number): string {
+  return `/home/${formatUserId(userId)}/.zos/recordings`;
+}
@@ -12,6 +12,7 @@ import {
   RequestTimeoutError,
   IdentityKeyAlreadyExistsError,
   MachineKeyNotFoundError,
+  InvalidShardError,
   StorageError,
 } from '../identity';
 
@@ -224,6 +225,55 @@ describe('IdentityServiceClient', () => {
     });
   });
 
+  describe('shard backup', () => {
+    it('should send shard blobs and typed shards for verification', async () => {
+      const userId = BigInt(12345);
+      const shards = ['ZOS1:0000:1:AB:00000000', { index: 3, hex: 'shard3hex' }];
+
+      const promise = client.verifyShards(userId, shards);
+
+      expect(supervisor.send_service_ipc).toHaveBeenCalledWith(
+        'identity',
+        MSG.VERIFY_SHARDS,
+        JSON.stringify({ user_id: '0x00000000000000000000000000003039', shards })
+      );
+
+      const response = {
+        result: {
+          Ok: {
+            valid_indices: [3],
+            rejected: [{ position: 0, index: null, problem: 'checksum_mismatch' }],
+            authenticated: true,
+            meets_threshold: false,
+            meets_threshold_with_password: true,
+          },
+        },
+      };
+      const requestId = (MSG.VERIFY_SHARDS + 1).toString(16).padStart(8, '0');
+      supervisor._simulateResponse(requestId, response);
+
+      await vi.advanceTimersByTimeAsync(20);
+
+      const report = await promise;
+      expect(report.rejected[0].problem).toBe('checksum_mismatch');
+      expect(report.meets_threshold_with_password).toBe(true);
+    });
+
+    it('should reject export of an invalid shard', async () => {
+      const promise = client.exportShard(BigInt(12345), { index: 2, hex: 'bad' });
+
+      const response = {
+        result: { Err: { InvalidShard: 'shard does not match your Neural Key' } },
+      };
+      const requestId = (MSG.EXPORT_SHARD + 1).toString(16).padStart(8, '0');
+      supervisor._simulateResponse(requestId, response);
+
+      await vi.advanceTimersByTimeAsync(20);
+
+      await expect(promise).rejects.toBeInstanceOf(InvalidShardError);
+    });
+  });
+
   describe('getIdentityKey', () => {
     it('should return key store when found', async () => {
       const userId = BigInt(12345);
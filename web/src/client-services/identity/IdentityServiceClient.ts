@@ -12,6 +12,9 @@ import {
   type Supervisor,
   type NeuralShard,
   type NeuralKeyGenerated,
+  type ShardInput,
+  type ShardSetReport,
+  type ShardBackupStatus,
   type LocalKeyStore,
   type MachineKeyRecord,
   type MachineKeyCapabilities,
@@ -23,6 +26,10 @@ import {
   type GenerateNeuralKeyResponse,
   type RecoverNeuralKeyResponse,
   type GetIdentityKeyResponse,
+  type ExportShardResponse,
+  type VerifyShardsResponse,
+  type MarkShardBackedUpResponse,
+  type GetShardBackupStatusResponse,
   type CreateMachineKeyResponse,
   type ListMachineKeysResponse,
   type RevokeMachineKeyResponse,
@@ -39,6 +46,8 @@ import {
   type GetIdentityPreferencesResponse,
   type SetDefaultKeySchemeResponse,
   type SetDefaultMachineKeyResponse,
+  type SetLoginItemsResponse,
+  type LoginItem,
   type MachineKeyAndTokens,
   type CreateMachineKeyAndEnrollResponse,
 } from './types';
@@ -157,6 +166,71 @@ export class IdentityServiceClient {
     return this.unwrapResult(response.result);
   }
 
+  // ===========================================================================
+  // Shard Backup Operations
+  // ===========================================================================
+
+  /**
+   * Encode one of the external shards as a QR-encodable backup blob.
+   *
+   * @param userId - User ID (as bigint or hex string)
+   * @param shard - External shard, as returned by generation
+   * @returns Blob using only QR alphanumeric characters, with a checksum
+   */
+  async exportShard(userId: bigint | string, shard: NeuralShard): Promise<string> {
+    const response = await this.request<ExportShardResponse>(MSG.EXPORT_SHARD, {
+      user_id: formatUserIdForRust(userId),
+      shard,
+    });
+    return this.unwrapResult(response.result);
+  }
+
+  /**
+   * Check whether a set of shards reaches the recovery threshold.
+   *
+   * The Neural Key is not reconstructed; each shard is checked against the
+   * commitment recorded when it was generated.
+   *
+   * @param userId - User ID (as bigint or hex string)
+   * @param shards - Scanned blobs and/or typed-in shards
+   * @returns Report of valid and rejected shards
+   */
+  async verifyShards(userId: bigint | string, shards: ShardInput[]): Promise<ShardSetReport> {
+    const response = await this.request<VerifyShardsResponse>(MSG.VERIFY_SHARDS, {
+      user_id: formatUserIdForRust(userId),
+      shards,
+    });
+    return this.unwrapResult(response.result);
+  }
+
+  /**
+   * Record a shard as backed up, after reading it back from the backup.
+   *
+   * @param userId - User ID (as bigint or hex string)
+   * @param shard - The shard read back from the backup (blob or typed in)
+   * @returns Updated backup status
+   */
+  async markShardBackedUp(userId: bigint | string, shard: ShardInput): Promise<ShardBackupStatus> {
+    const response = await this.request<MarkShardBackedUpResponse>(MSG.MARK_SHARD_BACKED_UP, {
+      user_id: formatUserIdForRust(userId),
+      shard,
+    });
+    return this.unwrapResult(response.result);
+  }
+
+  /**
+   * Get which external shards have been backed up.
+   *
+   * @param userId - User ID (as bigint or hex string)
+   */
+  async getShardBackupStatus(userId: bigint | string): Promise<ShardBackupStatus> {
+    const response = await this.request<GetShardBackupStatusResponse>(
+      MSG.GET_SHARD_BACKUP_STATUS,
+      { user_id: formatUserIdForRust(userId) }
+    );
+    return this.unwrapResult(response.result);
+  }
+
   // ===========================================================================
   // Machine Key Operations
   // ===========================================================================
@@ -565,6 +639,20 @@ export class IdentityServiceClient {
     this.unwrapResult(response.result);
   }
 
+  /**
+   * Replace the login items in VFS preferences.
+   * Items are launched in order after session start.
+   * @param userId - User ID
+   * @param items - Full login item list
+   */
+  async setLoginItems(userId: bigint | string, items: LoginItem[]): Promise<void> {
+    const response = await this.request<SetLoginItemsResponse>(MSG.SET_LOGIN_ITEMS, {
+      user_id: formatUserIdForRust(userId),
+      items,
+    });
+    this.unwrapResult(response.result);
+  }
+
   // ===========================================================================
   // Helpers
   // ===========================================================================
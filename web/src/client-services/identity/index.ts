@@ -13,6 +13,12 @@ export {
   type NeuralShard,
   type PublicIdentifiers,
   type NeuralKeyGenerated,
+  type ShardInput,
+  type ShardProblem,
+  type RejectedShard,
+  type ShardSetReport,
+  type ShardBackupStatus,
+  type ShardBackupStatusEntry,
   type KeyScheme,
   type MachineKeyCapability,
   type MachineKeyCapabilities,
@@ -25,6 +31,9 @@ export {
   type ZidTokens,
   type ZidSession,
   type MachineKeyAndTokens,
+  type LoginItem,
+  MAX_LOGIN_ITEMS,
+  MAX_LOGIN_ITEM_DELAY_MS,
   type Supervisor,
   type Result,
   type ResultOk,
@@ -17,6 +17,15 @@ export const MSG = {
   RECOVER_NEURAL_KEY_RESPONSE: 0x7057,
   GET_IDENTITY_KEY: 0x7052,
   GET_IDENTITY_KEY_RESPONSE: 0x7053,
+  // Shard backup operations
+  EXPORT_SHARD: 0x7058,
+  EXPORT_SHARD_RESPONSE: 0x7059,
+  VERIFY_SHARDS: 0x705a,
+  VERIFY_SHARDS_RESPONSE: 0x705b,
+  MARK_SHARD_BACKED_UP: 0x705c,
+  MARK_SHARD_BACKED_UP_RESPONSE: 0x705d,
+  GET_SHARD_BACKUP_STATUS: 0x705e,
+  GET_SHARD_BACKUP_STATUS_RESPONSE: 0x705f,
   // Credential operations
   ATTACH_EMAIL: 0x7040,
   ATTACH_EMAIL_RESPONSE: 0x7041,
@@ -57,6 +66,8 @@ export const MSG = {
   SET_DEFAULT_KEY_SCHEME_RESPONSE: 0x7093,
   SET_DEFAULT_MACHINE_KEY: 0x7094,
   SET_DEFAULT_MACHINE_KEY_RESPONSE: 0x7095,
+  SET_LOGIN_ITEMS: 0x7096,
+  SET_LOGIN_ITEMS_RESPONSE: 0x7097,
   // ZID Email Login
   ZID_LOGIN_EMAIL: 0x7088,
   ZID_LOGIN_EMAIL_RESPONSE: 0x7089,
@@ -93,6 +104,60 @@ export interface NeuralKeyGenerated {
   created_at: number;
 }
 
+/**
+ * A shard as submitted for verification or backup confirmation:
+ * either a scanned QR blob or a shard typed in from paper.
+ */
+export type ShardInput = string | NeuralShard;
+
+/** Why a shard was rejected (mirrors zos-identity/src/backup.rs) */
+export type ShardProblem =
+  | 'malformed'
+  | 'checksum_mismatch'
+  | 'wrong_user'
+  | 'unknown_index'
+  | 'duplicate'
+  | 'commitment_mismatch';
+
+/** A shard rejected during verification */
+export interface RejectedShard {
+  /** Position of the shard in the submitted set */
+  position: number;
+  /** Shard index, when it could be read */
+  index: number | null;
+  problem: ShardProblem;
+}
+
+/** Result of checking a shard set against the 3-shard threshold */
+export interface ShardSetReport {
+  /** Distinct shard indices that passed every check */
+  valid_indices: number[];
+  rejected: RejectedShard[];
+  /** False for keys generated before shard commitments were recorded */
+  authenticated: boolean;
+  /** The valid shards alone can recover the key */
+  meets_threshold: boolean;
+  /** The valid shards plus the password-protected shards can recover the key */
+  meets_threshold_with_password: boolean;
+}
+
+/** Backup status of one external shard */
+export interface ShardBackupStatusEntry {
+  index: number;
+  /** When the backup was confirmed (null if not yet) */
+  backed_up_at: number | null;
+}
+
+/** Backup status of a user's external shards */
+export interface ShardBackupStatus {
+  user_id: string;
+  shards: ShardBackupStatusEntry[];
+  /** Every external shard has been backed up */
+  complete: boolean;
+  /** Enough shards are backed up to recover without the password */
+  recoverable_without_password: boolean;
+}
+
 /** Key scheme for machine keys (matches Rust snake_case) */
 export type KeyScheme = 'classical' | 'pq_hybrid';
 
@@ -278,6 +343,22 @@ export interface GetIdentityKeyResponse {
   result: Result<LocalKeyStore | null>;
 }
 
+export interface ExportShardResponse {
+  result: Result<string>;
+}
+
+export interface VerifyShardsResponse {
+  result: Result<ShardSetReport>;
+}
+
+export interface MarkShardBackedUpResponse {
+  result: Result<ShardBackupStatus>;
+}
+
+export interface GetShardBackupStatusResponse {
+  result: Result<ShardBackupStatus>;
+}
+
 /** Request to create a machine key (mirrors Rust CreateMachineKeyRequest) */
 export interface CreateMachineKeyRequest {
   user_id: string;
@@ -363,8 +444,26 @@ export interface IdentityPreferences {
   default_key_scheme: KeyScheme;
   /** Default machine key ID for authentication (hex string) */
   default_machine_id?: string;
+  /** Apps launched when a session starts, in launch order */
+  login_items?: LoginItem[];
+}
+
+/** An app launched automatically after session start */
+export interface LoginItem {
+  /** App to launch (e.g. "terminal") */
+  app_id: string;
+  /** Disabled items stay in the list but are not launched */
+  enabled: boolean;
+  /** Delay after the session's core services are ready */
+  delay_ms: number;
 }
 
+/** Maximum login items per user (mirrors MAX_LOGIN_ITEMS) */
+export const MAX_LOGIN_ITEMS = 16;
+
+/** Maximum delay of a login item (mirrors MAX_LOGIN_ITEM_DELAY_MS) */
+export const MAX_LOGIN_ITEM_DELAY_MS = 60_000;
+
 /** Get identity preferences response */
 export interface GetIdentityPreferencesResponse {
   preferences: IdentityPreferences;
@@ -380,6 +479,11 @@ export interface SetDefaultMachineKeyResponse {
   result: Result<void>;
 }
 
+/** Set login items response */
+export interface SetLoginItemsResponse {
+  result: Result<void>;
+}
+
 // =============================================================================
 // Supervisor interface (re-exported from shared types)
 // =============================================================================
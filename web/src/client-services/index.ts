@@ -18,6 +18,10 @@ export {
   type LocalKeyStore,
   type Supervisor,
   type KeyScheme,
+  // Login items
+  type LoginItem,
+  MAX_LOGIN_ITEMS,
+  MAX_LOGIN_ITEM_DELAY_MS,
   // Capability format helpers
   isLegacyCapabilities,
   convertLegacyCapabilities,
@@ -70,6 +74,39 @@ export {
   TimeRequestTimeoutError,
 } from './TimeServiceClient';
 
+// Network service for the system-wide network policy and response cache
+export {
+  NetworkServiceClient,
+  NETWORK_MSG,
+  DEFAULT_NETWORK_POLICY,
+  type NetworkPolicy,
+  type NetworkCacheStats,
+  NetworkServiceError,
+} from './NetworkServiceClient';
+
+// Clipboard service for clipboard history and sync settings
+export {
+  ClipboardServiceClient,
+  CLIPBOARD_MSG,
+  DEFAULT_CLIPBOARD_SYNC,
+  type ClipboardEntry,
+  type ClipboardSyncSettings,
+  type CopyResult,
+  type SyncExclusion,
+  ClipboardServiceError,
+} from './ClipboardServiceClient';
+
+// Command service for the command palette
+export {
+  CommandServiceClient,
+  COMMAND_MSG,
+  type CommandMatch,
+  CommandServiceError,
+} from './CommandServiceClient';
+
+// VFS service for writes
+export { VfsServiceClient, VFS_MSG, VfsServiceError } from './VfsServiceClient';
+
 // VFS direct access for React components (reads only)
 // NOTE: Identity keys are stored in keystore at /keys/ paths, not in VFS
 export {
@@ -78,6 +115,7 @@ export {
   getUserHomeDir,
   getCredentialsPath,
   getZidSessionPath,
+  getInputRecordingsDir,
   type VfsInode,
 } from './VfsStorageClient';
 
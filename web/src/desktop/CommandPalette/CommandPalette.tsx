@@ -0,0 +1,169 @@
+import { useState, useEffect, useMemo, useRef, useCallback } from 'react';
+import { Panel } from '@cypher-asi/zui';
+import { CommandServiceClient, type CommandMatch } from '@/client-services';
+import type { Supervisor } from '../hooks/useSupervisor';
+import styles from './CommandPalette.module.css';
+
+// Apps the palette can open (same ids as the Begin menu)
+const LAUNCHABLE_APPS: Record<string, string> = {
+  terminal: 'Terminal',
+  files: 'Files',
+  calculator: 'Calculator',
+  clock: 'Clock',
+  settings: 'Settings',
+};
+
+/** Commands requested from the service per query */
+const MAX_RESULTS = 20;
+
+// =============================================================================
+// Component Props
+// =============================================================================
+
+export interface CommandPaletteProps {
+  supervisor: Supervisor;
+  /** Open an app by id */
+  onLaunchApp: (appId: string) => void;
+  /** Called when the palette should close */
+  onClose: () => void;
+}
+
+/** A palette row: an app to open or a registered command */
+type PaletteEntry =
+  | { kind: 'app'; appId: string; title: string }
+  | { kind: 'command'; command: CommandMatch };
+
+function entryKey(entry: PaletteEntry): string {
+  return entry.kind === 'app'
+    ? `app:${entry.appId}`
+    : `command:${entry.command.pid}:${entry.command.id}`;
+}
+
+/**
+ * Command Palette
+ *
+ * Lists "Open <app>" entries and the commands running apps registered with
+ * the command service, filtered by what is typed. The service ranks its
+ * commands; app entries match when every term is in the app name.
+ * Arrow keys move the selection, Enter runs it, Escape closes.
+ */
+export function CommandPalette({ supervisor, onLaunchApp, onClose }: CommandPaletteProps) {
+  const client = useMemo(() => new CommandServiceClient(supervisor), [supervisor]);
+  const inputRef = useRef<HTMLInputElement>(null);
+  const [query, setQuery] = useState('');
+  const [commands, setCommands] = useState<CommandMatch[]>([]);
+  const [selected, setSelected] = useState(0);
+
+  useEffect(() => {
+    inputRef.current?.focus();
+  }, []);
+
+  // Ask the service on every change; a late answer for an old query is dropped
+  useEffect(() => {
+    let stale = false;
+    client
+      .query(query, MAX_RESULTS)
+      .then((results) => {
+        if (!stale) setCommands(results);
+      })
+      .catch((err) => {
+        if (stale) return;
+        console.warn('[CommandPalette] Query failed:', err);
+        setCommands([]);
+      });
+    return () => {
+      stale = true;
+    };
+  }, [client, query]);
+
+  const entries = useMemo<PaletteEntry[]>(() => {
+    const terms = query.toLowerCase().split(/\s+/).filter(Boolean);
+    const apps: PaletteEntry[] = Object.entries(LAUNCHABLE_APPS)
+      .filter(([, name]) => terms.every((term) => `open ${name}`.toLowerCase().includes(term)))
+      .map(([appId, name]) => ({ kind: 'app', appId, title: `Open ${name}` }));
+    return [...apps, ...commands.map((command) => ({ kind: 'command' as const, command }))];
+  }, [query, commands]);
+
+  useEffect(() => {
+    setSelected(0);
+  }, [entries]);
+
+  const run = useCallback(
+    (entry: PaletteEntry) => {
+      onClose();
+      if (entry.kind === 'app') {
+        onLaunchApp(entry.appId);
+        return;
+      }
+      const { pid, id } = entry.command;
+      client.invoke(pid, id).catch((err) => {
+        console.warn(`[CommandPalette] Invoking ${id} in PID ${pid} failed:`, err);
+      });
+    },
+    [client, onClose, onLaunchApp]
+  );
+
+  const handleKeyDown = (e: React.KeyboardEvent<HTMLInputElement>) => {
+    switch (e.key) {
+      case 'Escape':
+        e.preventDefault();
+        onClose();
+        break;
+      case 'ArrowDown':
+        e.preventDefault();
+        setSelected((i) => (entries.length === 0 ? 0 : (i + 1) % entries.length));
+        break;
+      case 'ArrowUp':
+        e.preventDefault();
+        setSelected((i) => (entries.length === 0 ? 0 : (i - 1 + entries.length) % entries.length));
+        break;
+      case 'Enter':
+        e.preventDefault();
+        if (entries[selected]) run(entries[selected]);
+        break;
+    }
+  };
+
+  return (
+    <div className={styles.overlay} onClick={onClose} role="presentation">
+      <Panel
+        variant="glass"
+        className={styles.palette}
+        onClick={(e: React.MouseEvent) => e.stopPropagation()}
+        role="dialog"
+        aria-label="Command palette"
+      >
+        <input
+          ref={inputRef}
+          className={styles.input}
+          value={query}
+          placeholder="Type a command or app name"
+          onChange={(e) => setQuery(e.target.value)}
+          onKeyDown={handleKeyDown}
+          aria-label="Command"
+        />
+        {entries.length === 0 ? (
+          <div className={styles.empty}>No matching commands</div>
+        ) : (
+          <ul className={styles.results} role="listbox">
+            {entries.map((entry, i) => (
+              <li
+                key={entryKey(entry)}
+                className={`${styles.result} ${i === selected ? styles.resultSelected : ''}`}
+                role="option"
+                aria-selected={i === selected}
+                onMouseEnter={() => setSelected(i)}
+                onClick={() => run(entry)}
+              >
+                <span>{entry.kind === 'app' ? entry.title : entry.command.title}</span>
+                {entry.kind === 'command' && entry.command.app && (
+                  <span className={styles.resultApp}>{entry.command.app}</span>
+                )}
+              </li>
+            ))}
+          </ul>
+        )}
+      </Panel>
+    </div>
+  );
+}
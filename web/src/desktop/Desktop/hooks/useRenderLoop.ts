@@ -15,7 +15,11 @@
 
 import { useRef, useEffect, useState, useCallback } from 'react';
 import type { DesktopController } from '../../hooks/useSupervisor';
-import { syncStoresFromFrame, resetSyncState } from '../../sync';
+import {
+  syncStoresFromFrame,
+  resetSyncState,
+  registerBackgroundDisplaySettings,
+} from '../../sync';
 import type { WindowInfo, WorkspaceInfo, FrameData } from '@/stores/types';
 import type { DesktopBackgroundType } from '../types';
 import { windowListChanged } from '../types';
@@ -202,6 +206,8 @@ export function useRenderLoop({
     const canvas = canvasRef.current;
     if (!canvas) return;
 
+    let unsubscribeDisplay: (() => void) | null = null;
+
     // Set canvas size to match display size
     const updateCanvasSize = (): void => {
       const rect = canvas.getBoundingClientRect();
@@ -234,6 +240,7 @@ export function useRenderLoop({
         try {
           await Promise.race([initPromise, timeoutPromise]);
           backgroundRef.current = background;
+          unsubscribeDisplay = registerBackgroundDisplaySettings(background);
           console.log('[Desktop] Background initialized successfully');
           onBackgroundReady();
         } catch (initError) {
@@ -350,6 +357,7 @@ export function useRenderLoop({
 
     return () => {
       window.removeEventListener('resize', handleResize);
+      unsubscribeDisplay?.();
       if (animationFrameRef.current !== null) {
         cancelAnimationFrame(animationFrameRef.current);
       }
@@ -48,6 +48,7 @@ export interface DesktopBackgroundType {
   set_workspace_info(count: number, active: number, backgrounds_json: string): void;
   set_transitioning(transitioning: boolean): void;
   set_workspace_dimensions(width: number, height: number, gap: number): void;
+  set_display_settings_json(json: string): boolean;
 }
 
 export interface WorkspaceDimensions {
@@ -7,6 +7,7 @@
 import { useRef } from 'react';
 import type { DesktopController } from '../hooks/useSupervisor';
 import type { WorkspaceInfo } from '@/stores/types';
+import { useDesktopStore, selectFullscreenWindow } from '@/stores/desktopStore';
 import { WindowContent } from '../WindowContent';
 import { Taskbar } from '../Taskbar';
 import { AppRouter } from '@apps/AppRouter/AppRouter';
@@ -28,6 +29,7 @@ export function DesktopInner({
   workspaceInfoRef,
 }: DesktopInnerProps): JSX.Element {
   const canvasRef = useRef<HTMLCanvasElement>(null);
+  const fullscreenWindow = useDesktopStore(selectFullscreenWindow);
 
   const { windows, setWindowRef } = useRenderLoop({
     desktop,
@@ -51,7 +53,8 @@ export function DesktopInner({
           </WindowContent>
         ))}
 
-      <Taskbar />
+      {/* Fullscreen windows cover the taskbar area */}
+      {fullscreenWindow === null && <Taskbar />}
     </>
   );
 }
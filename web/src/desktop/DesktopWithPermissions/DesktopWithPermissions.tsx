@@ -4,11 +4,13 @@
  * Inner component that uses permissions hook and manages desktop state.
  */
 
-import { useRef, useEffect, useState } from 'react';
+import { useRef, useEffect, useState, useCallback } from 'react';
 import { usePermissions, PermissionsProvider } from '../hooks/usePermissions';
 import { useWindowActions } from '../hooks/useWindows';
 import { useKeyboardShortcuts } from '../hooks/useKeyboardShortcuts';
+import { useLoginItems } from '../hooks/useLoginItems';
 import { PermissionDialog } from '../PermissionDialog';
+import { CommandPalette } from '../CommandPalette';
 import { DesktopContextMenu } from '../DesktopContextMenu';
 import { useTheme } from '@cypher-asi/zui';
 import { BackgroundContext } from '../BackgroundContext';
@@ -26,6 +28,7 @@ export function DesktopWithPermissions({ supervisor, desktop }: DesktopProps): J
   const backgroundRef = useRef<DesktopBackgroundType | null>(null);
   const [initialized, setInitialized] = useState(false);
   const [selectionBox, setSelectionBox] = useState<SelectionBox | null>(null);
+  const [paletteOpen, setPaletteOpen] = useState(false);
 
   // Theme state from zui
   const { theme, accent, setTheme, setAccent } = useTheme();
@@ -34,7 +37,7 @@ export function DesktopWithPermissions({ supervisor, desktop }: DesktopProps): J
   const permissions = usePermissions();
 
   // Window actions (includes launchTerminal for spawning terminal with process)
-  const { launchTerminal } = useWindowActions();
+  const { launchApp, launchTerminal } = useWindowActions();
 
   // Ref to track current workspace info (updated by render loop in DesktopInner)
   const workspaceInfoRef = useRef<WorkspaceInfo | null>(null);
@@ -99,6 +102,8 @@ export function DesktopWithPermissions({ supervisor, desktop }: DesktopProps): J
       const index = parseInt(indexStr, 10);
       desktop.set_desktop_background(index, backgroundId);
     });
+
+    desktop.set_focus_policy(prefs.focusPolicy);
   }, [initialized, desktop]);
 
   // Handle resize
@@ -170,14 +175,32 @@ export function DesktopWithPermissions({ supervisor, desktop }: DesktopProps): J
     return () => window.removeEventListener('wheel', handleNativeWheel, { capture: true });
   }, []);
 
+  const openCommandPalette = useCallback(() => setPaletteOpen(true), []);
+  const closeCommandPalette = useCallback(() => setPaletteOpen(false), []);
+  const launchFromPalette = useCallback(
+    (appId: string) => {
+      // Terminal uses special spawn-and-link flow
+      if (appId === 'terminal') {
+        launchTerminal();
+      } else {
+        launchApp(appId);
+      }
+    },
+    [launchApp, launchTerminal]
+  );
+
   // Handle keyboard shortcuts for workspace navigation and void entry/exit
   useKeyboardShortcuts({
     initialized,
     desktop,
     supervisor,
     launchTerminal,
+    openCommandPalette,
   });
 
+  // Launch the user's login items once the session's services are up
+  useLoginItems({ initialized, launchApp, launchTerminal });
+
   // Compute selection box rectangle
   const selectionRect = selectionBox
     ? {
@@ -239,6 +262,15 @@ export function DesktopWithPermissions({ supervisor, desktop }: DesktopProps): J
             />
           )}
 
+          {/* Command Palette - opened with its hotkey */}
+          {paletteOpen && (
+            <CommandPalette
+              supervisor={supervisor}
+              onLaunchApp={launchFromPalette}
+              onClose={closeCommandPalette}
+            />
+          )}
+
           {/* Permission Dialog - shown when an app requests permissions */}
           {permissions.pendingRequest && (
             <PermissionDialog
@@ -7,6 +7,7 @@ import {
   SupervisorProvider,
 } from '../../hooks/useSupervisor';
 import { createMockDesktopController, createMockSupervisor } from '../../../../test/mocks';
+import { useAppHealthStore } from '@/stores';
 
 // Track the onChange callback for testing
 let _capturedOnChange: ((id: string) => void) | null = null;
@@ -80,6 +81,7 @@ describe('BeginMenu', () => {
     mockDesktop = createMockDesktopController();
     mockSupervisor = createMockSupervisor();
     onClose = vi.fn();
+    useAppHealthStore.setState({ crashReports: {} });
   });
 
   it('renders menu title', () => {
@@ -167,6 +169,31 @@ describe('BeginMenu', () => {
     expect(onClose).toHaveBeenCalled();
   });
 
+  it('offers remediation instead of launching an app in a crash loop', () => {
+    useAppHealthStore.getState().recordCrash({
+      name: 'calculator',
+      pid: 12,
+      exit_code: -1,
+      recent_crashes: 5,
+      failed: true,
+      time_ms: 0,
+    });
+    render(createElement(BeginMenu, { onClose }), {
+      wrapper: createTestWrapper(mockDesktop, mockSupervisor),
+    });
+
+    expect(screen.getByText('Calculator (failed)')).toBeInTheDocument();
+    expect(screen.queryByTestId('menu-item-calculator')).not.toBeInTheDocument();
+    expect(screen.getByText('Clear app data')).toBeInTheDocument();
+
+    fireEvent.click(screen.getByTestId('menu-item-calculator:retry'));
+
+    expect(mockSupervisor.reset_crashes).toHaveBeenCalledWith('calculator');
+    expect(mockDesktop.launch_app).not.toHaveBeenCalled();
+    expect(useAppHealthStore.getState().crashReports.calculator.failed).toBe(false);
+    expect(onClose).toHaveBeenCalled();
+  });
+
   it('closes menu on click outside', () => {
     const { container } = render(
       createElement('div', { 'data-testid': 'outside' }, createElement(BeginMenu, { onClose })),
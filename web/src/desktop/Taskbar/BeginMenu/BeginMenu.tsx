@@ -1,6 +1,15 @@
-import { useEffect, useRef } from 'react';
+import { useEffect, useMemo, useRef } from 'react';
 import { useWindowActions } from '../../hooks/useWindows';
 import { useSupervisor } from '../../hooks/useSupervisor';
+import { useIdentityServiceClient } from '../../hooks/useIdentityServiceClient';
+import {
+  useAppHealthStore,
+  selectCrashReports,
+  useSettingsStore,
+  selectLoginItems,
+  type CrashReport,
+} from '@/stores';
+import type { LoginItem } from '@/client-services';
 import { Menu, type MenuItem } from '@cypher-asi/zui';
 import { AppWindow, Calculator, Clock, Terminal, FolderOpen, Settings, Power } from 'lucide-react';
 import styles from './BeginMenu.module.css';
@@ -31,9 +40,43 @@ const MENU_ITEMS: MenuItem[] = [
   { id: 'shutdown', label: 'Shutdown', icon: <Power size={14} /> },
 ];
 
+/** Remediation actions offered for an app in a crash loop */
+type Remediation = 'retry' | 'clear-data' | 'disable-login';
+
+/**
+ * Replace every app in a crash loop with a "(failed)" submenu of
+ * remediation actions, with ids `<appId>:<action>`.
+ */
+function withRemediation(
+  items: MenuItem[],
+  crashReports: Record<string, CrashReport>,
+  loginItems: LoginItem[]
+): MenuItem[] {
+  return items.map((item) => {
+    if ('children' in item && item.children) {
+      return { ...item, children: withRemediation(item.children, crashReports, loginItems) };
+    }
+    if (!('id' in item) || !crashReports[item.id]?.failed) return item;
+
+    const children: MenuItem[] = [
+      { id: `${item.id}:retry`, label: 'Try again' },
+      { id: `${item.id}:clear-data`, label: 'Clear app data' },
+    ];
+    if (loginItems.some((login) => login.app_id === item.id && login.enabled)) {
+      children.push({ id: `${item.id}:disable-login`, label: 'Disable at login' });
+    }
+    return { ...item, label: `${item.label} (failed)`, children };
+  });
+}
+
 export function BeginMenu({ onClose, containerRef }: BeginMenuProps) {
   const { launchApp, launchTerminal } = useWindowActions();
   const supervisor = useSupervisor();
+  const { userId } = useIdentityServiceClient();
+  const crashReports = useAppHealthStore(selectCrashReports);
+  const clearFailed = useAppHealthStore((state) => state.clearFailed);
+  const loginItems = useSettingsStore(selectLoginItems);
+  const setLoginItems = useSettingsStore((state) => state.setLoginItems);
   const menuRef = useRef<HTMLDivElement>(null);
 
   useEffect(() => {
@@ -56,9 +99,46 @@ export function BeginMenu({ onClose, containerRef }: BeginMenuProps) {
     };
   }, [onClose, containerRef]);
 
+  const items = useMemo(
+    () => withRemediation(MENU_ITEMS, crashReports, loginItems),
+    [crashReports, loginItems]
+  );
+
+  const handleRemediation = (appId: string, action: Remediation) => {
+    switch (action) {
+      case 'clear-data':
+        // Start over from a clean namespace, then allow launching again
+        if (!supervisor || userId === null) return;
+        supervisor.delete_app_data(userId.toString(16), appId);
+        supervisor.reset_crashes(appId);
+        clearFailed(appId);
+        break;
+      case 'retry':
+        supervisor?.reset_crashes(appId);
+        clearFailed(appId);
+        break;
+      case 'disable-login':
+        if (userId === null) return;
+        setLoginItems(
+          userId,
+          loginItems.map((item) => (item.app_id === appId ? { ...item, enabled: false } : item))
+        ).catch(() => {
+          // Store reverts the list; error already logged
+        });
+        break;
+    }
+  };
+
   const handleSelect = async (id: string) => {
     // Skip parent menu items (submenus)
-    if (id === 'programs') return;
+    if (id === 'programs' || crashReports[id]?.failed) return;
+
+    const [appId, action] = id.split(':');
+    if (action) {
+      onClose();
+      handleRemediation(appId, action as Remediation);
+      return;
+    }
 
     if (id === 'shutdown') {
       onClose();
@@ -79,7 +159,7 @@ export function BeginMenu({ onClose, containerRef }: BeginMenuProps) {
     <div ref={menuRef} className={styles.menuWrapper}>
       <Menu
         title="ZERO OS"
-        items={MENU_ITEMS}
+        items={items}
         onChange={handleSelect}
         variant="glass"
         border="future"
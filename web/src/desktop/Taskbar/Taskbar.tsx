@@ -2,7 +2,14 @@ import { useState, useEffect, useCallback, useRef } from 'react';
 import { Button } from '@cypher-asi/zui';
 import { useWindowActions } from '../hooks/useWindows';
 import { useDesktopActions } from '../hooks/useDesktops';
-import { useWindowStore, selectWindows, useDesktopStore, selectDesktops } from '@/stores';
+import {
+  useWindowStore,
+  selectWindows,
+  useDesktopStore,
+  selectDesktops,
+  selectInVoid,
+  selectCarryTarget,
+} from '@/stores';
 import { BeginMenu } from './BeginMenu/BeginMenu';
 import { IdentityPanel } from './IdentityPanel';
 import { DateTime } from './DateTime';
@@ -43,8 +50,10 @@ export function Taskbar() {
   // Use Zustand stores directly for better performance
   const windows = useWindowStore(selectWindows);
   const desktops = useDesktopStore(selectDesktops);
+  const inVoid = useDesktopStore(selectInVoid);
+  const carryTarget = useDesktopStore(selectCarryTarget);
 
-  const { focusWindow, panToWindow, restoreWindow } = useWindowActions();
+  const { focusWindow, panToWindow, restoreWindow, startWindowCarry } = useWindowActions();
   const { createDesktop, switchDesktop } = useDesktopActions();
 
   // Toggle begin menu with 'z' key when not in an input field
@@ -94,6 +103,13 @@ export function Taskbar() {
     // If already focused and not minimized, do nothing - user already sees this window
   };
 
+  // In the void, dragging a window button onto a desktop tile moves the window there
+  const handleWindowPointerDown = (e: React.PointerEvent, windowId: number) => {
+    if (!inVoid || e.button !== 0) return;
+    e.stopPropagation();
+    startWindowCarry(windowId, e.clientX, e.clientY);
+  };
+
   const handleAddDesktop = () => {
     const count = desktops.length;
     createDesktop(`Desktop ${count + 1}`);
@@ -138,6 +154,7 @@ export function Taskbar() {
             icon={getWindowIcon(win.title)}
             className={`${styles.windowItem} ${win.state === 'minimized' ? styles.minimized : ''}`}
             onClick={(e) => handleWindowClick(e, win.id, win.state, win.focused)}
+            onPointerDown={(e) => handleWindowPointerDown(e, win.id)}
             title={win.title}
             selected={win.focused}
             selectedBgColor="transparent"
@@ -155,7 +172,7 @@ export function Taskbar() {
             variant={d.active ? 'glass' : 'transparent'}
             rounded="none"
             iconOnly
-            className={styles.workspaceBtn}
+            className={`${styles.workspaceBtn} ${carryTarget === i ? styles.workspaceDropTarget : ''}`}
             onClick={() => switchDesktop(i)}
             title={d.name}
             aria-label={`Switch to ${d.name}`}
@@ -33,6 +33,8 @@ export const WindowContent = forwardRef(function WindowContent(
   const dragStartRef = useRef<{ x: number; y: number; started: boolean } | null>(null);
 
   const isWidget = win.windowType === 'widget';
+  // Fullscreen windows are drawn without a frame
+  const isFullscreen = win.state === 'fullscreen';
 
   // Initial position using GPU-accelerated transform instead of left/top
   // Subsequent position updates happen directly via DOM, bypassing React
@@ -168,7 +170,7 @@ export const WindowContent = forwardRef(function WindowContent(
   return (
     <Panel
       ref={ref}
-      className={`${styles.window} ${win.focused ? styles.focused : ''} ${isWidget ? styles.widget : ''}`}
+      className={`${styles.window} ${win.focused ? styles.focused : ''} ${isWidget ? styles.widget : ''} ${isFullscreen ? styles.fullscreen : ''}`}
       variant="glass"
       border="future"
       style={style}
@@ -176,70 +178,74 @@ export const WindowContent = forwardRef(function WindowContent(
       onPointerDown={handleWindowPointerDown}
     >
       {/* Resize handles - directly start resize drag operation */}
-      <div
-        className={`${styles.resizeHandle} ${styles.resizeN}`}
-        style={{ height: handleSize }}
-        onPointerDown={handleResizeStart('n')}
-        role="separator"
-        aria-orientation="horizontal"
-        aria-label="Resize window top edge"
-        aria-valuenow={win.screenRect.y}
-      />
-      <div
-        className={`${styles.resizeHandle} ${styles.resizeS}`}
-        style={{ height: handleSize }}
-        onPointerDown={handleResizeStart('s')}
-        role="separator"
-        aria-orientation="horizontal"
-        aria-label="Resize window bottom edge"
-        aria-valuenow={win.screenRect.y + win.screenRect.height}
-      />
-      <div
-        className={`${styles.resizeHandle} ${styles.resizeE}`}
-        style={{ width: handleSize }}
-        onPointerDown={handleResizeStart('e')}
-        role="separator"
-        aria-orientation="vertical"
-        aria-label="Resize window right edge"
-        aria-valuenow={win.screenRect.x + win.screenRect.width}
-      />
-      <div
-        className={`${styles.resizeHandle} ${styles.resizeW}`}
-        style={{ width: handleSize }}
-        onPointerDown={handleResizeStart('w')}
-        role="separator"
-        aria-orientation="vertical"
-        aria-label="Resize window left edge"
-        aria-valuenow={win.screenRect.x}
-      />
-      {/* Corners use larger handles for easier diagonal targeting */}
-      <div
-        className={`${styles.resizeHandle} ${styles.resizeNE}`}
-        style={{ width: cornerSize, height: cornerSize }}
-        onPointerDown={handleResizeStart('ne')}
-        aria-label="Resize window top-right corner"
-      />
-      <div
-        className={`${styles.resizeHandle} ${styles.resizeNW}`}
-        style={{ width: cornerSize, height: cornerSize }}
-        onPointerDown={handleResizeStart('nw')}
-        aria-label="Resize window top-left corner"
-      />
-      <div
-        className={`${styles.resizeHandle} ${styles.resizeSE}`}
-        style={{ width: cornerSize, height: cornerSize }}
-        onPointerDown={handleResizeStart('se')}
-        aria-label="Resize window bottom-right corner"
-      />
-      <div
-        className={`${styles.resizeHandle} ${styles.resizeSW}`}
-        style={{ width: cornerSize, height: cornerSize }}
-        onPointerDown={handleResizeStart('sw')}
-        aria-label="Resize window bottom-left corner"
-      />
+      {!isFullscreen && (
+        <>
+          <div
+            className={`${styles.resizeHandle} ${styles.resizeN}`}
+            style={{ height: handleSize }}
+            onPointerDown={handleResizeStart('n')}
+            role="separator"
+            aria-orientation="horizontal"
+            aria-label="Resize window top edge"
+            aria-valuenow={win.screenRect.y}
+          />
+          <div
+            className={`${styles.resizeHandle} ${styles.resizeS}`}
+            style={{ height: handleSize }}
+            onPointerDown={handleResizeStart('s')}
+            role="separator"
+            aria-orientation="horizontal"
+            aria-label="Resize window bottom edge"
+            aria-valuenow={win.screenRect.y + win.screenRect.height}
+          />
+          <div
+            className={`${styles.resizeHandle} ${styles.resizeE}`}
+            style={{ width: handleSize }}
+            onPointerDown={handleResizeStart('e')}
+            role="separator"
+            aria-orientation="vertical"
+            aria-label="Resize window right edge"
+            aria-valuenow={win.screenRect.x + win.screenRect.width}
+          />
+          <div
+            className={`${styles.resizeHandle} ${styles.resizeW}`}
+            style={{ width: handleSize }}
+            onPointerDown={handleResizeStart('w')}
+            role="separator"
+            aria-orientation="vertical"
+            aria-label="Resize window left edge"
+            aria-valuenow={win.screenRect.x}
+          />
+          {/* Corners use larger handles for easier diagonal targeting */}
+          <div
+            className={`${styles.resizeHandle} ${styles.resizeNE}`}
+            style={{ width: cornerSize, height: cornerSize }}
+            onPointerDown={handleResizeStart('ne')}
+            aria-label="Resize window top-right corner"
+          />
+          <div
+            className={`${styles.resizeHandle} ${styles.resizeNW}`}
+            style={{ width: cornerSize, height: cornerSize }}
+            onPointerDown={handleResizeStart('nw')}
+            aria-label="Resize window top-left corner"
+          />
+          <div
+            className={`${styles.resizeHandle} ${styles.resizeSE}`}
+            style={{ width: cornerSize, height: cornerSize }}
+            onPointerDown={handleResizeStart('se')}
+            aria-label="Resize window bottom-right corner"
+          />
+          <div
+            className={`${styles.resizeHandle} ${styles.resizeSW}`}
+            style={{ width: cornerSize, height: cornerSize }}
+            onPointerDown={handleResizeStart('sw')}
+            aria-label="Resize window bottom-left corner"
+          />
+        </>
+      )}
 
       {/* Standard window: Title bar with title and buttons */}
-      {!isWidget && (
+      {!isWidget && !isFullscreen && (
         <div
           className={`${styles.titleBar} ${win.appId === 'settings' ? styles.titleBarBordered : ''}`}
           style={{ height: FRAME_STYLE.titleBarHeight }}
@@ -257,7 +263,7 @@ export const WindowContent = forwardRef(function WindowContent(
       )}
 
       {/* Widget window: Floating close button only */}
-      {isWidget && (
+      {isWidget && !isFullscreen && (
         <div className={styles.widgetCloseButton} onPointerDown={(e) => e.stopPropagation()}>
           <ButtonWindow action="close" size="sm" rounded="none" onClick={handleClose} />
         </div>
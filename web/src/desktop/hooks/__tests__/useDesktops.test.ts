@@ -8,7 +8,9 @@ import {
   useViewMode,
   useIsInVoid,
   useVoidActions,
+  useDesktopTemplates,
 } from '../useDesktops';
+import { useDesktopPrefsStore } from '@/stores/desktopPrefsStore';
 import { DesktopControllerProvider } from '../useSupervisor';
 import { createMockDesktopController } from '../../../../test/mocks';
 
@@ -260,3 +262,46 @@ describe('useVoidActions', () => {
     expect(() => result.current.exitVoid(0)).not.toThrow();
   });
 });
+
+describe('useDesktopTemplates', () => {
+  beforeEach(() => {
+    useDesktopPrefsStore.setState({ templates: {} });
+  });
+
+  it('saves a desktop as a template and instantiates it', async () => {
+    const mockDesktop = createMockDesktopController();
+
+    const wrapper = ({ children }: { children: React.ReactNode }) =>
+      createElement(DesktopControllerProvider, { value: mockDesktop }, children);
+
+    const { result } = renderHook(() => useDesktopTemplates(), { wrapper });
+
+    act(() => {
+      expect(result.current.saveAsTemplate(0, 'Writing')).toBe(true);
+    });
+    expect(result.current.templates['Writing']).toBeDefined();
+
+    const index = await result.current.instantiateTemplate('Writing');
+    expect(index).toBe(mockDesktop._state.desktops.length - 1);
+    expect(mockDesktop.create_desktop_from_template_json).toHaveBeenCalled();
+
+    act(() => {
+      result.current.removeTemplate('Writing');
+    });
+    expect(result.current.templates['Writing']).toBeUndefined();
+  });
+
+  it('reports invalid folder bindings', () => {
+    const mockDesktop = createMockDesktopController();
+    mockDesktop.set_desktop_folders_json = vi.fn(() => 'folder path must be absolute');
+
+    const wrapper = ({ children }: { children: React.ReactNode }) =>
+      createElement(DesktopControllerProvider, { value: mockDesktop }, children);
+
+    const { result } = renderHook(() => useDesktopTemplates(), { wrapper });
+
+    expect(result.current.setDesktopFolders(0, [{ name: 'project', path: 'code' }])).toBe(
+      'folder path must be absolute'
+    );
+  });
+});
@@ -0,0 +1,48 @@
+import { describe, it, expect, vi, beforeEach, afterEach } from 'vitest';
+import { scheduleLoginItems } from '../useLoginItems';
+
+describe('scheduleLoginItems', () => {
+  beforeEach(() => {
+    vi.useFakeTimers();
+  });
+
+  afterEach(() => {
+    vi.useRealTimers();
+  });
+
+  it('launches enabled items after their delays', () => {
+    const launch = vi.fn();
+    scheduleLoginItems(
+      [
+        { app_id: 'terminal', enabled: true, delay_ms: 0 },
+        { app_id: 'clock', enabled: false, delay_ms: 0 },
+        { app_id: 'files', enabled: true, delay_ms: 2000 },
+      ],
+      launch
+    );
+
+    vi.advanceTimersByTime(0);
+    expect(launch).toHaveBeenCalledTimes(1);
+    expect(launch).toHaveBeenCalledWith('terminal');
+
+    vi.advanceTimersByTime(2000);
+    expect(launch).toHaveBeenCalledTimes(2);
+    expect(launch).toHaveBeenLastCalledWith('files');
+  });
+
+  it('cancels launches not yet made', () => {
+    const launch = vi.fn();
+    const cancel = scheduleLoginItems(
+      [
+        { app_id: 'terminal', enabled: true, delay_ms: 0 },
+        { app_id: 'files', enabled: true, delay_ms: 5000 },
+      ],
+      launch
+    );
+
+    vi.advanceTimersByTime(0);
+    cancel();
+    vi.advanceTimersByTime(5000);
+    expect(launch).toHaveBeenCalledTimes(1);
+  });
+});
@@ -32,10 +32,13 @@ export { useZeroIdAuth } from './useZeroIdAuth';
 // Desktop/window hooks
 export { useDesktops } from './useDesktops';
 export { useWindows } from './useWindows';
+export { useLoginItems, scheduleLoginItems } from './useLoginItems';
 
 // Permission hooks
 export { usePermissions } from './usePermissions';
 
 // Utility hooks
 export { useKeyboardShortcuts } from './useKeyboardShortcuts';
+export { useInputRecorder } from './useInputRecorder';
+export type { ReplayReport, UseInputRecorderReturn } from './useInputRecorder';
 export { useCopyToClipboard } from './useCopyToClipboard';
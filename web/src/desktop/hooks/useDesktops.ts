@@ -1,5 +1,6 @@
 import { useCallback } from 'react';
-import { useDesktopController } from './useSupervisor';
+import { useDesktopController, useSupervisor } from './useSupervisor';
+import { isProcessApp, spawnAppProcess } from './useWindows';
 import {
   useDesktopStore,
   selectDesktops,
@@ -10,14 +11,16 @@ import {
   type DesktopInfo,
   type ViewMode,
   type LayerOpacities,
+  type DesktopTemplate,
+  type FolderBinding,
 } from '@/stores';
-import { useDesktopPrefsStore } from '@/stores/desktopPrefsStore';
+import { useDesktopPrefsStore, selectTemplates } from '@/stores/desktopPrefsStore';
 
 // =============================================================================
 // Re-export Types from Store
 // =============================================================================
 
-export type { DesktopInfo, ViewMode, LayerOpacities };
+export type { DesktopInfo, ViewMode, LayerOpacities, DesktopTemplate, FolderBinding };
 
 // =============================================================================
 // Return Types
@@ -31,6 +34,20 @@ export interface UseDesktopActionsReturn {
   switchDesktop: (index: number) => void;
 }
 
+/** Return type for useDesktopTemplates hook */
+export interface UseDesktopTemplatesReturn {
+  /** Saved templates by name */
+  templates: Record<string, DesktopTemplate>;
+  /** Save a desktop's apps, layout and folders as a template */
+  saveAsTemplate: (desktopIndex: number, name: string) => boolean;
+  /** Create a desktop from a saved template, spawning its apps; returns the desktop index */
+  instantiateTemplate: (name: string) => Promise<number | null>;
+  /** Delete a saved template */
+  removeTemplate: (name: string) => void;
+  /** Bind VFS folders to a desktop; returns an error message or null */
+  setDesktopFolders: (desktopIndex: number, folders: FolderBinding[]) => string | null;
+}
+
 /** Return type for useVoidActions hook */
 export interface UseVoidActionsReturn {
   /** Enter void (overview) mode */
@@ -93,6 +110,72 @@ export function useDesktopActions(): UseDesktopActionsReturn {
   };
 }
 
+// Hook for desktop templates (project workspaces)
+export function useDesktopTemplates(): UseDesktopTemplatesReturn {
+  const desktop = useDesktopController();
+  const supervisor = useSupervisor();
+  const templates = useDesktopPrefsStore(selectTemplates);
+
+  const saveAsTemplate = useCallback(
+    (desktopIndex: number, name: string): boolean => {
+      if (!desktop || !name.trim()) return false;
+      try {
+        const template = JSON.parse(
+          desktop.get_desktop_template_json(desktopIndex)
+        ) as DesktopTemplate | null;
+        if (!template) return false;
+        useDesktopPrefsStore.getState().saveTemplate({ ...template, name: name.trim() });
+        return true;
+      } catch (e) {
+        console.error('[useDesktops] Error saving template:', e);
+        return false;
+      }
+    },
+    [desktop]
+  );
+
+  const instantiateTemplate = useCallback(
+    async (name: string): Promise<number | null> => {
+      const template = useDesktopPrefsStore.getState().templates[name];
+      if (!desktop || !template) return null;
+
+      // Windows are laid out by the engine; processes are spawned here
+      // and linked to their windows.
+      const result = JSON.parse(
+        desktop.create_desktop_from_template_json(JSON.stringify(template))
+      ) as { desktop: number; windows: Array<{ id: number; appId: string }> } | null;
+      if (!result) return null;
+
+      if (supervisor) {
+        for (const window of result.windows) {
+          if (!isProcessApp(window.appId)) continue;
+          const pid = await spawnAppProcess(supervisor, window.appId);
+          if (pid !== null) {
+            desktop.set_window_process_id(BigInt(window.id), pid);
+          }
+        }
+      }
+      return result.desktop;
+    },
+    [desktop, supervisor]
+  );
+
+  const removeTemplate = useCallback((name: string): void => {
+    useDesktopPrefsStore.getState().removeTemplate(name);
+  }, []);
+
+  const setDesktopFolders = useCallback(
+    (desktopIndex: number, folders: FolderBinding[]): string | null => {
+      if (!desktop) return null;
+      const error = desktop.set_desktop_folders_json(desktopIndex, JSON.stringify(folders));
+      return error || null;
+    },
+    [desktop]
+  );
+
+  return { templates, saveAsTemplate, instantiateTemplate, removeTemplate, setDesktopFolders };
+}
+
 /**
  * Hook to get the current view mode.
  *
@@ -0,0 +1,87 @@
+import { useCallback, useMemo, useState } from 'react';
+import { useDesktopController, useSupervisor } from './useSupervisor';
+import { useIdentityStore, selectCurrentUser } from '@/stores';
+import {
+  VfsServiceClient,
+  VfsStorageClient,
+  getInputRecordingsDir,
+  userIdToBigInt,
+} from '@/client-services';
+
+/** Result of replaying a recording (the engine's ScriptReport) */
+export interface ReplayReport {
+  outputs: unknown[];
+  failure: { step: number; message: string } | null;
+  now_ms: number;
+}
+
+/** Return type for useInputRecorder hook */
+export interface UseInputRecorderReturn {
+  /** Whether pointer input is being recorded */
+  isRecording: boolean;
+  /** Start recording pointer input */
+  start: () => void;
+  /** Stop recording and save it as `<name>.json` in the user's recordings folder; returns the path */
+  stop: (name: string) => Promise<string | null>;
+  /** Replay a saved recording through the engine's pointer handlers */
+  replay: (path: string) => ReplayReport | null;
+}
+
+/**
+ * Hook for recording pointer gestures to the VFS and replaying them.
+ *
+ * Recordings land in `/home/<user>/.zos/recordings/`. Replays run on the
+ * engine's virtual clock, so a recorded gesture plays back the same way
+ * every time - useful for demos and for reproducing input bugs. Replays
+ * assume the same screen size and window layout as when recorded.
+ */
+export function useInputRecorder(): UseInputRecorderReturn {
+  const desktop = useDesktopController();
+  const supervisor = useSupervisor();
+  const currentUser = useIdentityStore(selectCurrentUser);
+  const userId = useMemo(() => userIdToBigInt(currentUser?.id), [currentUser?.id]);
+  const [isRecording, setIsRecording] = useState(false);
+
+  const start = useCallback(() => {
+    if (!desktop) return;
+    desktop.start_input_recording();
+    setIsRecording(true);
+  }, [desktop]);
+
+  const stop = useCallback(
+    async (name: string): Promise<string | null> => {
+      if (!desktop) return null;
+      const json = desktop.stop_input_recording();
+      setIsRecording(false);
+      if (json === 'null' || !supervisor || userId === null) return null;
+
+      const dir = getInputRecordingsDir(userId);
+      const path = `${dir}/${name}.json`;
+      try {
+        const vfs = new VfsServiceClient(supervisor);
+        await vfs.mkdir(dir);
+        await vfs.writeFile(path, json);
+        return path;
+      } catch (err) {
+        console.error('[useInputRecorder] Failed to save recording:', err);
+        return null;
+      }
+    },
+    [desktop, supervisor, userId]
+  );
+
+  const replay = useCallback(
+    (path: string): ReplayReport | null => {
+      if (!desktop) return null;
+      const json = VfsStorageClient.readTextSync(path);
+      if (json === null) {
+        console.warn('[useInputRecorder] Recording not found:', path);
+        return null;
+      }
+      return JSON.parse(desktop.replay_input_recording(json)) as ReplayReport;
+    },
+    [desktop]
+  );
+
+  return { isRecording, start, stop, replay };
+}
@@ -0,0 +1,105 @@
+import { useEffect, useRef } from 'react';
+import {
+  useIdentityStore,
+  selectCurrentSession,
+  useSettingsStore,
+  useAppHealthStore,
+} from '@/stores';
+import { ServiceNotFoundError, type LoginItem } from '@/client-services';
+import { useIdentityServiceClient } from './useIdentityServiceClient';
+
+/** Wait between attempts while the identity service is not registered yet */
+const SERVICE_RETRY_MS = 1000;
+
+/** Attempts before giving up on the identity service */
+const MAX_SERVICE_ATTEMPTS = 30;
+
+interface UseLoginItemsOptions {
+  initialized: boolean;
+  launchApp: (appId: string) => void;
+  launchTerminal: () => void;
+}
+
+/**
+ * Launch the enabled login items in order, each after its delay.
+ *
+ * @returns A function cancelling the launches not yet made
+ */
+export function scheduleLoginItems(
+  items: LoginItem[],
+  launch: (appId: string) => void
+): () => void {
+  const timers = items
+    .filter((item) => item.enabled)
+    .map((item) => setTimeout(() => launch(item.app_id), item.delay_ms));
+  return () => timers.forEach(clearTimeout);
+}
+
+/**
+ * Hook launching the user's login items once per session.
+ *
+ * The items live in the identity preferences, so reading them waits until
+ * the identity service (and the VFS and keystore it depends on) is up:
+ * while it is not registered yet the read is retried. Delays count from
+ * then. Logging out cancels the launches still pending. Apps in a crash
+ * loop are not launched.
+ */
+export function useLoginItems({
+  initialized,
+  launchApp,
+  launchTerminal,
+}: UseLoginItemsOptions): void {
+  const session = useIdentityStore(selectCurrentSession);
+  const sessionId = session?.id ?? null;
+  const { client, userId, isReady } = useIdentityServiceClient();
+
+  // Launchers change identity across renders; the schedule should not
+  const launchRef = useRef({ launchApp, launchTerminal });
+  launchRef.current = { launchApp, launchTerminal };
+
+  useEffect(() => {
+    if (!initialized || !isReady || !client || userId === null || !sessionId) return;
+
+    let cancelled = false;
+    let cancelLaunches: (() => void) | null = null;
+    let retryTimer: ReturnType<typeof setTimeout> | null = null;
+
+    const launch = (appId: string) => {
+      if (useAppHealthStore.getState().crashReports[appId]?.failed) {
+        console.warn(`[useLoginItems] Not launching ${appId}: it is in a crash loop`);
+        return;
+      }
+      // Terminal uses special spawn-and-link flow
+      if (appId === 'terminal') {
+        launchRef.current.launchTerminal();
+      } else {
+        launchRef.current.launchApp(appId);
+      }
+    };
+
+    const start = async (attempt: number) => {
+      try {
+        const prefs = await client.getIdentityPreferences(userId);
+        if (cancelled) return;
+        const items = prefs.login_items ?? [];
+        useSettingsStore.setState({ loginItems: items });
+        cancelLaunches = scheduleLoginItems(items, launch);
+        console.log('[useLoginItems] Scheduled login items:', items);
+      } catch (err) {
+        if (cancelled) return;
+        if (err instanceof ServiceNotFoundError && attempt < MAX_SERVICE_ATTEMPTS) {
+          retryTimer = setTimeout(() => start(attempt + 1), SERVICE_RETRY_MS);
+          return;
+        }
+        console.error('[useLoginItems] Failed to load login items:', err);
+      }
+    };
+    start(1);
+
+    return () => {
+      cancelled = true;
+      if (retryTimer) clearTimeout(retryTimer);
+      cancelLaunches?.();
+    };
+  }, [initialized, isReady, client, userId, sessionId]);
+}
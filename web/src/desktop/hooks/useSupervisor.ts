@@ -40,12 +40,53 @@ export interface DesktopController {
   minimize_window(id: bigint): void;
   maximize_window(id: bigint): void;
   restore_window(id: bigint): void;
+  toggle_fullscreen(id: bigint): void;
+  exit_fullscreen(id: bigint): void;
+  get_fullscreen_window(): bigint | undefined;
   get_focused_window(): bigint | undefined;
+  /** Restore, focus and pan to a window, following the focus policy across desktops */
+  request_focus(id: bigint): void;
+  /** 'switch_desktop' or 'pull_window'; returns false for an unknown policy */
+  set_focus_policy(policy: string): boolean;
+  get_focus_policy(): string;
   pan_to_window(id: bigint): void;
+  zoom_to_window(id: bigint): void;
+  zoom_to_fit_all(): void;
+  zoom_to_rect(x: number, y: number, width: number, height: number): void;
   get_windows_json(): string;
   get_window_screen_rects_json(): string;
   launch_app(app_id: string): bigint;
 
+  // Minimap
+  set_minimap_visible(visible: boolean): void;
+  get_minimap_json(): string;
+  /** Center the camera on a normalized (0-1) minimap point */
+  minimap_navigate(x: number, y: number): void;
+
+  // Annotations
+  /** Select a tool (AnnotationTool JSON), or stop annotating with "null" */
+  set_annotation_tool_json(json: string): boolean;
+  /** Returns false if not annotating (the pointer event should go to pointer_down) */
+  begin_stroke(x: number, y: number, pressure: number): boolean;
+  extend_stroke(x: number, y: number, pressure: number): void;
+  end_stroke(): bigint | undefined;
+  undo_stroke(): boolean;
+  clear_annotations(desktop_index: number): boolean;
+  /** Active desktop's strokes (AnnotationStroke[]) in canvas coordinates */
+  get_annotations_json(): string;
+
+  // Widgets
+  /** Add a widget (WidgetKind JSON); centered in view without x/y. Returns -1 on failure */
+  add_widget_json(desktop_index: number, kind_json: string, x?: number, y?: number): number;
+  update_widget_json(id: bigint, kind_json: string): boolean;
+  move_widget(id: bigint, x: number, y: number): boolean;
+  resize_widget(id: bigint, width: number, height: number): boolean;
+  remove_widget(id: bigint): boolean;
+  /** A desktop's widgets (Widget[]) in canvas coordinates */
+  get_widgets_json(desktop_index: number): string;
+  widget_at(x: number, y: number): bigint | undefined;
+  start_widget_drag(id: bigint, x: number, y: number): void;
+
   // Desktops (workspaces)
   create_desktop(name: string): number;
   switch_desktop(index: number): void;
@@ -53,6 +94,22 @@ export interface DesktopController {
   get_visual_active_desktop(): number;
   get_desktops_json(): string;
   get_desktop_dimensions_json(): string;
+  move_window_to_desktop(window_id: bigint, desktop_index: number): boolean;
+  rename_desktop(index: number, name: string): boolean;
+  move_desktop(from: number, to: number): boolean;
+  /** Returns an empty string on success, otherwise the error message */
+  delete_desktop(index: number, rehome_to: number): string;
+  /** Returns the copy's index, or -1 on error */
+  duplicate_desktop(index: number): number;
+  get_desktop_template_json(index: number): string;
+  /** Returns `{"desktop", "windows": [{"id", "appId"}]}`, or "null" if the template is invalid */
+  create_desktop_from_template_json(json: string): string;
+  /** Returns an empty string on success, otherwise the error message */
+  set_desktop_folders_json(index: number, json: string): string;
+  get_desktop_folders_json(index: number): string;
+  export_snapshot_json(): string;
+  /** Returns an empty string on success, otherwise the error message */
+  import_snapshot_json(json: string): string;
 
   // Void mode
   get_view_mode(): string;
@@ -66,6 +123,23 @@ export interface DesktopController {
   is_transitioning(): boolean;
   tick_transition(): boolean;
 
+  // Accessibility
+  /** DisplaySettings JSON plus `color_matrix`, the filter's row-major 3x3 matrix */
+  get_display_settings_json(): string;
+  /** Missing fields take their defaults; returns false for invalid JSON */
+  set_display_settings_json(json: string): boolean;
+
+  // Keyboard
+  /** Returns KeyResult JSON: handled, unhandled, or a frontend command */
+  handle_key(key: string, ctrl: boolean, shift: boolean, alt: boolean, meta: boolean): string;
+  /** Run a KeyCommand given as JSON; returns KeyResult JSON */
+  run_key_command_json(json: string): string;
+  /** Cheat sheet: HotkeyCheatSheetEntry[] as JSON */
+  get_hotkeys_json(): string;
+  /** Returns an empty string on success, otherwise the error message */
+  bind_hotkey(chord: string, command_json: string): string;
+  unbind_hotkey(chord: string): boolean;
+
   // Input handling
   pointer_down(x: number, y: number, button: number, ctrl: boolean, shift: boolean): string;
   pointer_move(x: number, y: number): string;
@@ -73,6 +147,19 @@ export interface DesktopController {
   wheel(dx: number, dy: number, x: number, y: number, ctrl: boolean): string;
   start_window_resize(window_id: bigint, direction: string, x: number, y: number): void;
   start_window_drag(window_id: bigint, x: number, y: number): void;
+  /** Carry a window in the void; releasing over a desktop tile moves it there */
+  start_window_carry(window_id: bigint, x: number, y: number): void;
+
+  // Input recording
+  /** Start recording pointer input (pointer_*, wheel and start_window_* calls) */
+  start_input_recording(): void;
+  /** Stop recording; returns InputRecording JSON, or "null" if not recording */
+  stop_input_recording(): string;
+  is_recording_input(): boolean;
+  /** Replay InputRecording JSON on a virtual clock; returns ScriptReport JSON */
+  replay_input_recording(json: string): string;
+  /** Desktop tile under a screen point in the void, or -1 */
+  get_desktop_tile_at(x: number, y: number): number;
 
   // Unified frame tick
   tick_frame(): string;
@@ -1,5 +1,5 @@
 import { useCallback } from 'react';
-import { useDesktopController, useSupervisor } from './useSupervisor';
+import { useDesktopController, useSupervisor, type Supervisor } from './useSupervisor';
 import {
   useWindowStore,
   selectWindows,
@@ -37,12 +37,18 @@ export interface UseWindowActionsReturn {
   focusWindow: (id: number) => void;
   /** Pan viewport to center on a window */
   panToWindow: (id: number) => void;
+  /** Bring a window to the user, even from another desktop (app-initiated focus) */
+  requestFocus: (id: number) => void;
   /** Minimize a window */
   minimizeWindow: (id: number) => void;
   /** Maximize a window */
   maximizeWindow: (id: number) => void;
   /** Restore a window from minimized/maximized state */
   restoreWindow: (id: number) => void;
+  /** Move a window to another desktop */
+  moveWindowToDesktop: (id: number, desktopIndex: number) => void;
+  /** Start carrying a window over the desktop tiles in the void */
+  startWindowCarry: (id: number, x: number, y: number) => void;
   /** Launch an app by ID */
   launchApp: (appId: string) => number | null;
   /** Launch a terminal with its own process */
@@ -78,8 +84,54 @@ export function useFocusedWindow(): number | null {
   return useWindowStore(selectFocusedId);
 }
 
-// Module-level cache for terminal WASM binary
-let terminalWasmCache: Uint8Array | null = null;
+// Apps that run in their own process, by app ID -> process binary URL
+const PROCESS_APPS: Record<string, string> = {
+  terminal: '/processes/terminal.wasm',
+};
+
+// Module-level cache for process WASM binaries
+const processWasmCache = new Map<string, Uint8Array>();
+
+/** Whether an app's windows are backed by their own process */
+export function isProcessApp(appId: string): boolean {
+  return appId in PROCESS_APPS;
+}
+
+/**
+ * Spawn the process for a process-backed app.
+ *
+ * Returns the PID, or null if the app has no process binary or the spawn
+ * failed. The caller links it to a window with set_window_process_id().
+ */
+export async function spawnAppProcess(
+  supervisor: Supervisor,
+  appId: string
+): Promise<bigint | null> {
+  const url = PROCESS_APPS[appId];
+  if (!url) return null;
+
+  try {
+    let binary = processWasmCache.get(appId);
+    if (!binary) {
+      console.log(`[useWindows] Fetching ${url}...`);
+      const response = await fetch(url);
+      if (!response.ok) {
+        console.error(`[useWindows] Failed to fetch ${url}:`, response.status);
+        return null;
+      }
+      binary = new Uint8Array(await response.arrayBuffer());
+      processWasmCache.set(appId, binary);
+      console.log(`[useWindows] Loaded ${url}:`, binary.length, 'bytes');
+    }
+
+    const pid = supervisor.complete_spawn(appId, binary);
+    console.log(`[useWindows] Spawned ${appId} process with PID:`, pid);
+    return pid;
+  } catch (e) {
+    console.error(`[useWindows] Error spawning ${appId}:`, e);
+    return null;
+  }
+}
 
 // Hook for window actions
 export function useWindowActions(): UseWindowActionsReturn {
@@ -137,6 +189,13 @@ export function useWindowActions(): UseWindowActionsReturn {
     [desktop]
   );
 
+  const requestFocus = useCallback(
+    (id: number) => {
+      desktop?.request_focus(BigInt(id));
+    },
+    [desktop]
+  );
+
   const minimizeWindow = useCallback(
     (id: number) => {
       desktop?.minimize_window(BigInt(id));
@@ -158,6 +217,20 @@ export function useWindowActions(): UseWindowActionsReturn {
     [desktop]
   );
 
+  const moveWindowToDesktop = useCallback(
+    (id: number, desktopIndex: number) => {
+      desktop?.move_window_to_desktop(BigInt(id), desktopIndex);
+    },
+    [desktop]
+  );
+
+  const startWindowCarry = useCallback(
+    (id: number, x: number, y: number) => {
+      desktop?.start_window_carry(BigInt(id), x, y);
+    },
+    [desktop]
+  );
+
   const launchApp = useCallback(
     (appId: string) => {
       if (!desktop) return null;
@@ -172,27 +245,15 @@ export function useWindowActions(): UseWindowActionsReturn {
     if (!supervisor || !desktop) return null;
 
     try {
-      // 1. Fetch terminal WASM binary (use cache if available)
-      if (!terminalWasmCache) {
-        console.log('[useWindows] Fetching terminal.wasm...');
-        const response = await fetch('/processes/terminal.wasm');
-        if (!response.ok) {
-          console.error('[useWindows] Failed to fetch terminal.wasm:', response.status);
-          return null;
-        }
-        terminalWasmCache = new Uint8Array(await response.arrayBuffer());
-        console.log('[useWindows] Loaded terminal.wasm:', terminalWasmCache.length, 'bytes');
-      }
-
-      // 2. Spawn the terminal process FIRST (before creating window)
-      const pid = supervisor.complete_spawn('terminal', terminalWasmCache);
-      console.log('[useWindows] Spawned terminal process with PID:', pid);
+      // 1. Spawn the terminal process FIRST (before creating window)
+      const pid = await spawnAppProcess(supervisor, 'terminal');
+      if (pid === null) return null;
 
-      // 3. Create the window
+      // 2. Create the window
       const windowId = desktop.launch_app('terminal');
       console.log('[useWindows] Created terminal window:', windowId);
 
-      // 4. Link window to process (this also updates the title to show PID)
+      // 3. Link window to process (this also updates the title to show PID)
       desktop.set_window_process_id(windowId, pid);
       console.log('[useWindows] Linked window', windowId, 'to process', pid);
 
@@ -221,12 +282,14 @@ export function useWindowActions(): UseWindowActionsReturn {
         const existingWindow = windows.find((w) => w.appId === appId);
 
         if (existingWindow) {
-          // Window exists - focus it and optionally restore if minimized
-          if (existingWindow.state === 'minimized' && restoreMinimized) {
-            desktop.restore_window(BigInt(existingWindow.id));
+          if (existingWindow.state === 'minimized' && !restoreMinimized) {
+            desktop.focus_window(BigInt(existingWindow.id));
+            desktop.pan_to_window(BigInt(existingWindow.id));
+            return existingWindow.id;
           }
-          desktop.focus_window(BigInt(existingWindow.id));
-          desktop.pan_to_window(BigInt(existingWindow.id));
+          // Window exists - bring it to the user, switching desktops or
+          // pulling it over per the focus policy if it's on another desktop
+          desktop.request_focus(BigInt(existingWindow.id));
           return existingWindow.id;
         } else {
           // No existing window - launch new one
@@ -246,9 +309,12 @@ export function useWindowActions(): UseWindowActionsReturn {
     closeWindow,
     focusWindow,
     panToWindow,
+    requestFocus,
     minimizeWindow,
     maximizeWindow,
     restoreWindow,
+    moveWindowToDesktop,
+    startWindowCarry,
     launchApp,
     launchTerminal,
     launchOrFocusApp,
@@ -5,6 +5,15 @@ import { Desktop } from './Desktop';
 import { OSLoading } from './OSLoading';
 import { Supervisor, DesktopController } from './hooks/useSupervisor';
 import { useSettingsStore } from '@/stores';
+import {
+  registerSpeechOutput,
+  registerPrintDialog,
+  registerPermissionPrompts,
+  registerThumbnailDecoder,
+  registerNotifications,
+  registerCrashReports,
+  registerDisplaySettings,
+} from './sync';
 import '@cypher-asi/zui/styles';
 import '@/styles/global.css';
 
@@ -250,7 +259,7 @@ function App() {
         // System messages are buffered until a callback is registered.
 
         // Set up spawn callback for loading WASM processes
-        supervisor.set_spawn_callback((procType: string, name: string) => {
+        supervisor.set_spawn_callback((procType: string, name: string, requestId: bigint) => {
           setTimeout(async () => {
             try {
               const wasmFile = procType === 'terminal' ? 'terminal.wasm' : `${procType}.wasm`;
@@ -265,14 +274,35 @@ function App() {
               const binary = new Uint8Array(await response.arrayBuffer());
               console.log(`[spawn] Loaded ${binary.length} bytes, spawning ${name}...`);
 
-              const pid = supervisor.complete_spawn(name, binary);
-              console.log(`[spawn] complete_spawn returned PID ${pid}`);
+              const pid = supervisor.complete_requested_spawn(requestId, binary);
+              console.log(`[spawn] complete_requested_spawn returned PID ${pid}`);
             } catch (e) {
               console.error(`[spawn] Error:`, e);
             }
           }, 0);
         });
 
+        // Speak SpeechService output and announce focus changes
+        registerSpeechOutput(supervisor);
+
+        // Show PrintService jobs in the browser's print dialog
+        registerPrintDialog(supervisor);
+
+        // Ask the user before PermissionService grants sensitive capabilities
+        registerPermissionPrompts(supervisor);
+
+        // Decode images for ThumbnailService thumbnails
+        registerThumbnailDecoder(supervisor);
+
+        // Show service notifications (alarms)
+        registerNotifications(supervisor);
+
+        // Track service and app crash loops
+        registerCrashReports(supervisor);
+
+        // Apply accessibility display settings (contrast, color filter, motion)
+        registerDisplaySettings(desktop);
+
         // Initialize Axiom storage
         updateProgress(BOOT_STEPS.AXIOM, 'Initializing Axiom storage...');
         try {
@@ -0,0 +1,27 @@
+/**
+ * Crash Sync - Crash reports from Init into the app health store.
+ *
+ * Init counts the crashes of every service and app and reports each one.
+ * Once crashes repeat too often the report is marked failed, and the
+ * launcher offers remediation instead of launching the app again.
+ */
+
+import { useAppHealthStore, type CrashReport } from '@/stores';
+import type { Supervisor } from '../hooks/useSupervisor';
+
+/**
+ * Register the crash callback.
+ *
+ * @param supervisor - The Rust supervisor instance
+ */
+export function registerCrashReports(supervisor: Supervisor): void {
+  supervisor.set_crash_callback((json: string) => {
+    const report: CrashReport = JSON.parse(json);
+    if (report.failed) {
+      console.warn(
+        `[crash] ${report.name} is in a crash loop (${report.recent_crashes} recent crashes)`
+      );
+    }
+    useAppHealthStore.getState().recordCrash(report);
+  });
+}
@@ -0,0 +1,110 @@
+/**
+ * Display Sync - Applies accessibility display settings.
+ *
+ * The settings live in the persisted settings store. This pushes them to:
+ * - the desktop engine (reduced motion skips crossfades and camera animations)
+ * - the background renderer (color filter, high contrast, frozen animation)
+ * - the document, as data attributes the global styles key off
+ *   (data-contrast, data-reduced-motion, data-color-filter)
+ *
+ * The color filter for the DOM is an SVG feColorMatrix built from the
+ * matrix the engine reports, so the page and the background always agree.
+ */
+
+import type { DesktopController } from '../hooks/useSupervisor';
+import type { DesktopBackgroundType } from '../Desktop/types';
+import { useSettingsStore, selectDisplaySettings } from '@/stores';
+import type { DisplaySettings } from '@/stores';
+
+const FILTER_ID = 'zos-color-filter';
+
+/** Row-major 3x3 matrix as an feColorMatrix `values` list (4x5, alpha untouched) */
+function feColorMatrixValues(matrix: number[][]): string {
+  const rows = matrix.map((row) => [...row, 0, 0].join(' '));
+  return [...rows, '0 0 0 1 0'].join(' ');
+}
+
+/** Create or update the hidden SVG holding the DOM color filter */
+function applyColorFilter(settings: DisplaySettings, matrix: number[][] | undefined): void {
+  const root = document.documentElement;
+  let svg = document.getElementById(`${FILTER_ID}-svg`);
+
+  if (settings.color_filter === 'none' || !matrix) {
+    delete root.dataset.colorFilter;
+    svg?.remove();
+    return;
+  }
+
+  if (!svg) {
+    const ns = 'http://www.w3.org/2000/svg';
+    svg = document.createElementNS(ns, 'svg');
+    svg.id = `${FILTER_ID}-svg`;
+    svg.setAttribute('aria-hidden', 'true');
+    svg.setAttribute('style', 'position:absolute;width:0;height:0');
+    const filter = document.createElementNS(ns, 'filter');
+    filter.id = FILTER_ID;
+    filter.setAttribute('color-interpolation-filters', 'sRGB');
+    const colorMatrix = document.createElementNS(ns, 'feColorMatrix');
+    colorMatrix.setAttribute('type', 'matrix');
+    filter.appendChild(colorMatrix);
+    svg.appendChild(filter);
+    document.body.appendChild(svg);
+  }
+
+  svg.querySelector('feColorMatrix')?.setAttribute('values', feColorMatrixValues(matrix));
+  root.dataset.colorFilter = settings.color_filter;
+}
+
+function applyToDocument(settings: DisplaySettings, matrix: number[][] | undefined): void {
+  const root = document.documentElement;
+  if (settings.high_contrast) {
+    root.dataset.contrast = 'high';
+  } else {
+    delete root.dataset.contrast;
+  }
+  if (settings.reduced_motion) {
+    root.dataset.reducedMotion = '';
+  } else {
+    delete root.dataset.reducedMotion;
+  }
+  applyColorFilter(settings, matrix);
+}
+
+/**
+ * Apply display settings to the desktop engine and the document, now and
+ * whenever they change.
+ *
+ * @param desktop - The Rust desktop controller
+ * @returns Cleanup function that stops following the settings store
+ */
+export function registerDisplaySettings(desktop: DesktopController): () => void {
+  const apply = (settings: DisplaySettings): void => {
+    if (!desktop.set_display_settings_json(JSON.stringify(settings))) {
+      console.warn('[display] Invalid display settings:', settings);
+      return;
+    }
+    const applied = JSON.parse(desktop.get_display_settings_json()) as {
+      color_matrix?: number[][];
+    };
+    applyToDocument(settings, applied.color_matrix);
+  };
+
+  apply(selectDisplaySettings(useSettingsStore.getState()));
+  return useSettingsStore.subscribe(selectDisplaySettings, apply);
+}
+
+/**
+ * Apply display settings to the background renderer, now and whenever they
+ * change.
+ *
+ * @param background - An initialized DesktopBackground
+ * @returns Cleanup function that stops following the settings store
+ */
+export function registerBackgroundDisplaySettings(background: DesktopBackgroundType): () => void {
+  const apply = (settings: DisplaySettings): void => {
+    background.set_display_settings_json(JSON.stringify(settings));
+  };
+
+  apply(selectDisplaySettings(useSettingsStore.getState()));
+  return useSettingsStore.subscribe(selectDisplaySettings, apply);
+}
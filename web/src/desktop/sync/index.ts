@@ -6,3 +6,10 @@
 
 export { syncStoresFromFrame, resetSyncState } from './renderLoopSync';
 export { registerStoreCallbacks, type CallbackCleanup } from './callbackSync';
+export { registerSpeechOutput } from './speechSync';
+export { registerPrintDialog } from './printSync';
+export { registerPermissionPrompts } from './permissionPromptSync';
+export { registerThumbnailDecoder } from './thumbnailSync';
+export { registerNotifications } from './notificationSync';
+export { registerCrashReports } from './crashSync';
+export { registerDisplaySettings, registerBackgroundDisplaySettings } from './displaySync';
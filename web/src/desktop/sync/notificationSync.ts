@@ -0,0 +1,37 @@
+/**
+ * Notification Sync - Service notifications through the browser's Notification API.
+ *
+ * Services such as the TimeService post notifications (alarms) that the
+ * supervisor forwards to this callback. Without notification permission
+ * they are logged to the console instead.
+ */
+
+import type { Supervisor } from '../hooks/useSupervisor';
+
+interface ServiceNotification {
+  title: string;
+  body: string;
+  source: string;
+  tag: string;
+}
+
+/**
+ * Register the notification callback.
+ *
+ * @param supervisor - The Rust supervisor instance
+ */
+export function registerNotifications(supervisor: Supervisor): void {
+  const api = typeof window !== 'undefined' ? window.Notification : undefined;
+  if (api?.permission === 'default') {
+    void api.requestPermission();
+  }
+
+  supervisor.set_notification_callback((json: string) => {
+    const notification: ServiceNotification = JSON.parse(json);
+    if (api?.permission !== 'granted') {
+      console.log(`[notification] ${notification.title}: ${notification.body}`);
+      return;
+    }
+    new api(notification.title, { body: notification.body, tag: notification.tag });
+  });
+}
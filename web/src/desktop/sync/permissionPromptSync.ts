@@ -0,0 +1,47 @@
+/**
+ * Permission Prompt Sync - Ask the user before sensitive capability grants.
+ *
+ * PermissionService holds some requests (like a Debugger capability, which
+ * can read any app's memory) until the user answers. The supervisor forwards
+ * each one to this callback, and the answer goes back through
+ * `permission_prompt_answered`. Every prompt must be answered or the
+ * requesting app waits forever.
+ */
+
+import type { Supervisor } from '../hooks/useSupervisor';
+
+interface PermissionPrompt {
+  id: number;
+  pid: number;
+  app: string;
+  object_type: number;
+  object_name: string;
+  permissions: number;
+  reason: string;
+}
+
+/** The question put to the user for a prompt. */
+export function promptMessage(prompt: PermissionPrompt): string {
+  const app = prompt.app || `Process ${prompt.pid}`;
+  const reason = prompt.reason ? `\n\nReason given: ${prompt.reason}` : '';
+  return `${app} (PID ${prompt.pid}) is asking for ${prompt.object_name} access.${reason}\n\nAllow it?`;
+}
+
+/**
+ * Register the permission prompt callback.
+ *
+ * @param supervisor - The Rust supervisor instance
+ */
+export function registerPermissionPrompts(supervisor: Supervisor): void {
+  supervisor.set_permission_prompt_callback((json: string) => {
+    const prompt: PermissionPrompt = JSON.parse(json);
+    if (typeof window === 'undefined') {
+      supervisor.permission_prompt_answered(prompt.id, false);
+      return;
+    }
+    // Let the current frame finish before the dialog blocks
+    setTimeout(() => {
+      supervisor.permission_prompt_answered(prompt.id, window.confirm(promptMessage(prompt)));
+    }, 0);
+  });
+}
@@ -0,0 +1,145 @@
+/**
+ * Print Sync - Print jobs through the browser's print dialog.
+ *
+ * The PrintService queues print jobs and the supervisor forwards the one to
+ * show now to this callback. The document is laid out in a hidden iframe
+ * (pages as SVG, HTML as-is) and printed from there. Each job must be
+ * reported finished so the service shows the next one.
+ *
+ * Browsers don't say whether the user printed or dismissed the dialog, so
+ * a dialog that closes normally is reported as "printed".
+ */
+
+import type { Supervisor } from '../hooks/useSupervisor';
+
+type Color = [number, number, number];
+
+type PrintItem =
+  | {
+      type: 'text';
+      x: number;
+      y: number;
+      text: string;
+      size: number;
+      bold: boolean;
+      color: Color;
+    }
+  | {
+      type: 'line';
+      x1: number;
+      y1: number;
+      x2: number;
+      y2: number;
+      width: number;
+      color: Color;
+    }
+  | {
+      type: 'rect';
+      x: number;
+      y: number;
+      width: number;
+      height: number;
+      fill: Color | null;
+      stroke: Color | null;
+      line_width: number;
+    };
+
+type PrintDocument =
+  | {
+      kind: 'pages';
+      page_size: { width: number; height: number };
+      pages: { items: PrintItem[] }[];
+    }
+  | { kind: 'html'; html: string };
+
+interface PrintDialogJob {
+  id: number;
+  pid: number;
+  title: string;
+  document: PrintDocument;
+}
+
+function escapeXml(text: string): string {
+  return text
+    .replace(/&/g, '&amp;')
+    .replace(/</g, '&lt;')
+    .replace(/>/g, '&gt;')
+    .replace(/"/g, '&quot;');
+}
+
+function rgb(color: Color | null): string {
+  return color ? `rgb(${color[0]},${color[1]},${color[2]})` : 'none';
+}
+
+function renderItem(item: PrintItem): string {
+  switch (item.type) {
+    case 'text':
+      return `<text x="${item.x}" y="${item.y}" font-family="Helvetica, Arial, sans-serif" font-size="${item.size}" font-weight="${item.bold ? 'bold' : 'normal'}" fill="${rgb(item.color)}" xml:space="preserve">${escapeXml(item.text)}</text>`;
+    case 'line':
+      return `<line x1="${item.x1}" y1="${item.y1}" x2="${item.x2}" y2="${item.y2}" stroke="${rgb(item.color)}" stroke-width="${item.width}"/>`;
+    case 'rect':
+      return `<rect x="${item.x}" y="${item.y}" width="${item.width}" height="${item.height}" fill="${rgb(item.fill)}" stroke="${rgb(item.stroke)}" stroke-width="${item.line_width}"/>`;
+  }
+}
+
+/** Lay a document out as a standalone HTML page. */
+export function documentToHtml(title: string, document: PrintDocument): string {
+  if (document.kind === 'html') {
+    return document.html;
+  }
+  const { width, height } = document.page_size;
+  const pages = document.pages
+    .map(
+      (page) =>
+        `<svg xmlns="http://www.w3.org/2000/svg" width="${width}pt" height="${height}pt" viewBox="0 0 ${width} ${height}">${page.items.map(renderItem).join('')}</svg>`
+    )
+    .join('\n');
+  return `<!DOCTYPE html><html><head><title>${escapeXml(title)}</title><style>
+@page { size: ${width}pt ${height}pt; margin: 0; }
+body { margin: 0; }
+svg { display: block; page-break-after: always; }
+</style></head><body>${pages}</body></html>`;
+}
+
+/**
+ * Register the print callback.
+ *
+ * @param supervisor - The Rust supervisor instance
+ */
+export function registerPrintDialog(supervisor: Supervisor): void {
+  supervisor.set_print_callback((json: string) => {
+    const job: PrintDialogJob = JSON.parse(json);
+    if (typeof document === 'undefined') {
+      supervisor.print_finished(job.id, 'failed', 'Printing is not available');
+      return;
+    }
+
+    const frame = document.createElement('iframe');
+    frame.style.position = 'fixed';
+    frame.style.width = '0';
+    frame.style.height = '0';
+    frame.style.border = '0';
+    frame.setAttribute('sandbox', 'allow-modals allow-same-origin');
+    frame.srcdoc = documentToHtml(job.title, job.document);
+
+    const finish = (outcome: 'printed' | 'failed', error?: string) => {
+      supervisor.print_finished(job.id, outcome, error);
+      frame.remove();
+    };
+    frame.onload = () => {
+      const view = frame.contentWindow;
+      if (!view) {
+        finish('failed', 'Print dialog could not be opened');
+        return;
+      }
+      try {
+        // print() blocks until the dialog closes
+        view.print();
+        finish('printed');
+      } catch (e) {
+        finish('failed', String(e));
+      }
+    };
+    document.body.appendChild(frame);
+  });
+}
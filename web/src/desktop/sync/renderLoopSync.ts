@@ -21,6 +21,9 @@ let prevAnimating = false;
 let prevTransitioning = false;
 let prevViewMode = 'desktop';
 let prevShowVoid = false;
+let prevFullscreenWindow: number | null = null;
+let prevCarryTarget: number | null = null;
+let prevAnnotationRevision = 0;
 let prevActiveIndex = 0;
 let prevDesktopCount = 0;
 
@@ -84,6 +87,9 @@ export function syncStoresFromFrame(frame: FrameData): void {
   const desktopChanged =
     frame.viewMode !== prevViewMode ||
     frame.showVoid !== prevShowVoid ||
+    frame.fullscreenWindow !== prevFullscreenWindow ||
+    frame.carryTarget !== prevCarryTarget ||
+    frame.annotationRevision !== prevAnnotationRevision ||
     activeIndexChanged ||
     desktopCountChanged;
 
@@ -91,6 +97,9 @@ export function syncStoresFromFrame(frame: FrameData): void {
     desktopStore.syncFromFrame({
       viewMode: frame.viewMode,
       showVoid: frame.showVoid,
+      fullscreenWindow: frame.fullscreenWindow,
+      carryTarget: frame.carryTarget,
+      annotationRevision: frame.annotationRevision,
       viewport: frame.viewport,
       workspaceInfo: frame.workspaceInfo,
     });
@@ -98,6 +107,9 @@ export function syncStoresFromFrame(frame: FrameData): void {
     // Update tracking
     prevViewMode = frame.viewMode;
     prevShowVoid = frame.showVoid;
+    prevFullscreenWindow = frame.fullscreenWindow;
+    prevCarryTarget = frame.carryTarget;
+    prevAnnotationRevision = frame.annotationRevision;
     prevActiveIndex = frame.workspaceInfo.active;
   }
 
@@ -138,6 +150,9 @@ export function resetSyncState(): void {
   prevTransitioning = false;
   prevViewMode = 'desktop';
   prevShowVoid = false;
+  prevFullscreenWindow = null;
+  prevCarryTarget = null;
+  prevAnnotationRevision = 0;
   prevActiveIndex = 0;
   prevDesktopCount = 0;
 }
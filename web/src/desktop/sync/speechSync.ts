@@ -0,0 +1,55 @@
+/**
+ * Speech Sync - Screen reader output through the browser's speechSynthesis.
+ *
+ * The SpeechService queues utterances and the supervisor forwards the one to
+ * speak now to this callback. Each utterance must be reported finished
+ * (including on error or cancel) so the service moves on to the next.
+ *
+ * Focus changes are announced through supervisor.announce(), which does
+ * nothing unless the accessibility.announce_focus flag is on.
+ */
+
+import type { Supervisor } from '../hooks/useSupervisor';
+import { useWindowStore, selectFocusedWindow } from '@/stores';
+
+interface SpeechUtterance {
+  id: number;
+  pid: number;
+  text: string;
+  priority: 'low' | 'normal' | 'high';
+}
+
+/**
+ * Register the speech callback and focus announcements.
+ *
+ * @param supervisor - The Rust supervisor instance
+ * @returns Cleanup function that stops announcing focus changes
+ */
+export function registerSpeechOutput(supervisor: Supervisor): () => void {
+  const synth = typeof window !== 'undefined' ? window.speechSynthesis : undefined;
+
+  supervisor.set_speech_callback((action: string, json: string) => {
+    if (action === 'cancel') {
+      // Fires onerror ('interrupted') for the current utterance, which reports it done
+      synth?.cancel();
+      return;
+    }
+
+    const utterance: SpeechUtterance = JSON.parse(json);
+    if (!synth) {
+      supervisor.speech_finished(utterance.id);
+      return;
+    }
+    const spoken = new SpeechSynthesisUtterance(utterance.text);
+    const finish = () => supervisor.speech_finished(utterance.id);
+    spoken.onend = finish;
+    spoken.onerror = finish;
+    synth.speak(spoken);
+  });
+
+  return useWindowStore.subscribe(selectFocusedWindow, (focused, previous) => {
+    if (focused && focused.id !== previous?.id) {
+      supervisor.announce(`${focused.title}, window`);
+    }
+  });
+}
@@ -0,0 +1,73 @@
+/**
+ * Thumbnail Sync - Image decoding for the ThumbnailService.
+ *
+ * The ThumbnailService sends image files to decode through the supervisor.
+ * The browser decodes and scales them with createImageBitmap, and the
+ * result is encoded as WebP (PNG where WebP encoding isn't supported) small
+ * enough to fit in one IPC message.
+ */
+
+import type { Supervisor } from '../hooks/useSupervisor';
+
+/** Encoder qualities tried in turn until the thumbnail fits */
+const QUALITIES = [0.85, 0.7, 0.5, 0.3];
+
+/** Scale `width` x `height` to fit in `size` x `size`, never enlarging. */
+export function fitWithin(
+  width: number,
+  height: number,
+  size: number
+): { width: number; height: number } {
+  const scale = Math.min(1, size / Math.max(width, height));
+  return {
+    width: Math.max(1, Math.round(width * scale)),
+    height: Math.max(1, Math.round(height * scale)),
+  };
+}
+
+async function makeThumbnail(
+  image: Uint8Array,
+  size: number,
+  maxBytes: number
+): Promise<{ width: number; height: number; data: Uint8Array }> {
+  const source = await createImageBitmap(new Blob([image]));
+  const { width, height } = fitWithin(source.width, source.height, size);
+  source.close();
+
+  const bitmap = await createImageBitmap(new Blob([image]), {
+    resizeWidth: width,
+    resizeHeight: height,
+    resizeQuality: 'high',
+  });
+  const canvas = new OffscreenCanvas(width, height);
+  const context = canvas.getContext('2d');
+  if (!context) {
+    bitmap.close();
+    throw new Error('Canvas is not available');
+  }
+  context.drawImage(bitmap, 0, 0);
+  bitmap.close();
+
+  for (const quality of QUALITIES) {
+    const blob = await canvas.convertToBlob({ type: 'image/webp', quality });
+    if (blob.size <= maxBytes) {
+      return { width, height, data: new Uint8Array(await blob.arrayBuffer()) };
+    }
+  }
+  throw new Error(`Thumbnail exceeds ${maxBytes} bytes`);
+}
+
+/**
+ * Decode images sent by the ThumbnailService and report the thumbnails.
+ */
+export function registerThumbnailDecoder(supervisor: Supervisor): void {
+  supervisor.set_thumbnail_callback((id: number, size: number, image: Uint8Array) => {
+    if (typeof createImageBitmap === 'undefined' || typeof OffscreenCanvas === 'undefined') {
+      supervisor.thumbnail_failed(id, 'Image decoding is not available');
+      return;
+    }
+    makeThumbnail(image, size, supervisor.thumbnail_max_bytes())
+      .then(({ width, height, data }) => supervisor.thumbnail_finished(id, width, height, data))
+      .catch((e) => supervisor.thumbnail_failed(id, String(e)));
+  });
+}
@@ -49,9 +49,13 @@ export interface Supervisor {
   // ===========================================================================
 
   /** Set callback for spawn requests from processes */
-  set_spawn_callback(callback: (procType: string, name: string) => void): void;
-  /** Complete a spawn request with the binary */
+  set_spawn_callback(
+    callback: (procType: string, name: string, requestId: bigint) => void
+  ): void;
+  /** Spawn a process with the binary */
   complete_spawn(name: string, binary: Uint8Array): bigint;
+  /** Complete a spawn request passed to the spawn callback with the binary */
+  complete_requested_spawn(requestId: bigint, binary: Uint8Array): bigint;
 
   // ===========================================================================
   // Storage & Axiom
@@ -115,6 +119,8 @@ export interface Supervisor {
   get_processes_with_capabilities_json(): string;
   /** Get endpoint list as JSON */
   get_endpoint_list_json(): string;
+  /** Get the service graph (processes, capability and IPC edges) as JSON */
+  get_service_graph_json(): string;
   /** Get recent IPC traffic as JSON */
   get_ipc_traffic_json(count: number): string;
   /** Get system metrics as JSON */
@@ -125,6 +131,17 @@ export interface Supervisor {
   get_commitlog_json(count: number): string;
   /** Get system log entries as JSON */
   get_syslog_json(count: number): string;
+  /** Get in-flight storage/keystore/network operations as JSON, oldest first */
+  get_async_ops_json(): string;
+  /** Get per-process traffic counted by the network service as JSON */
+  get_network_stats_json(): string;
+
+  // ===========================================================================
+  // Async Operation Recovery
+  // ===========================================================================
+
+  /** Fail every async operation outstanding for at least minAgeMs; returns how many */
+  fail_stuck_async_ops(minAgeMs: number): number;
 
   // ===========================================================================
   // Capability Management
@@ -149,6 +166,71 @@ export interface Supervisor {
    */
   set_ipc_response_callback(callback: (requestId: string, data: string) => void): void;
 
+  // ===========================================================================
+  // Speech Output
+  // ===========================================================================
+
+  /** Register the callback that speaks SpeechService utterances ("speak" | "cancel") */
+  set_speech_callback(callback: (action: string, json: string) => void): void;
+  /** Report that an utterance finished or failed so the next one starts */
+  speech_finished(utteranceId: number): void;
+  /** Announce a focus change (no-op unless accessibility.announce_focus is on) */
+  announce(text: string): boolean;
+
+  // ===========================================================================
+  // Printing
+  // ===========================================================================
+
+  /** Register the callback that shows PrintService jobs ({ id, pid, title, document }) */
+  set_print_callback(callback: (json: string) => void): void;
+  /** Report how a print dialog closed so the next job is shown */
+  print_finished(jobId: number, outcome: 'printed' | 'cancelled' | 'failed', error?: string): void;
+
+  // ===========================================================================
+  // Permission Prompts
+  // ===========================================================================
+
+  /** Register the callback that asks the user about PermissionService requests ({ id, pid, app, object_name, reason, ... }) */
+  set_permission_prompt_callback(callback: (json: string) => void): void;
+  /** Relay the user's answer to a permission prompt; returns false if it could not be sent */
+  permission_prompt_answered(promptId: number, granted: boolean): boolean;
+
+  // ===========================================================================
+  // Thumbnails
+  // ===========================================================================
+
+  /** Register the callback that decodes ThumbnailService images to fit in size x size */
+  set_thumbnail_callback(callback: (id: number, size: number, image: Uint8Array) => void): void;
+  /** Largest encoded thumbnail thumbnail_finished accepts, in bytes */
+  thumbnail_max_bytes(): number;
+  /** Report a decoded thumbnail (PNG, JPEG or WebP) */
+  thumbnail_finished(id: number, width: number, height: number, data: Uint8Array): void;
+  /** Report that an image could not be decoded */
+  thumbnail_failed(id: number, error: string): void;
+
+  // ===========================================================================
+  // Notifications
+  // ===========================================================================
+
+  /** Register the callback that shows service notifications ({ title, body, source, tag }) */
+  set_notification_callback(callback: (json: string) => void): void;
+
+  // ===========================================================================
+  // Crash reports
+  // ===========================================================================
+
+  /**
+   * Register the callback told about service and app crashes
+   * ({ name, pid, exit_code, recent_crashes, failed, time_ms })
+   */
+  set_crash_callback(callback: (json: string) => void): void;
+  /** Last crash report of every service and app that has crashed (JSON array) */
+  get_crash_reports_json(): string;
+  /** Clear the crash loop of a service or app (a stopped service is restarted) */
+  reset_crashes(name: string): boolean;
+  /** Delete an app's data in the user's namespace (user ID as hex) */
+  delete_app_data(user_id: string, app_id: string): boolean;
+
   /**
    * Send an IPC message to a named service.
    *
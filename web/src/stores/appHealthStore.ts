@@ -0,0 +1,73 @@
+/**
+ * App Health Store - Crash reports of services and apps.
+ *
+ * Init reports every crash through the supervisor's crash callback. A crash
+ * report marked `failed` ended in a crash loop: the service is no longer
+ * restarted, and the launcher shows the app as failed with remediation
+ * actions instead of launching it, until its crash loop is reset.
+ */
+
+import { create } from 'zustand';
+import { subscribeWithSelector } from 'zustand/middleware';
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/** Last crash of a service or app, as reported by the supervisor */
+export interface CrashReport {
+  /** Process name (the app id for apps) */
+  name: string;
+  /** PID of the instance that crashed */
+  pid: number;
+  /** Exit code it died with */
+  exit_code: number;
+  /** Crashes within Init's crash-loop window, this one included */
+  recent_crashes: number;
+  /** In a crash loop: not restarted or launched until reset */
+  failed: boolean;
+  /** Wall-clock time of the report (ms since the Unix epoch) */
+  time_ms: number;
+}
+
+interface AppHealthStoreState {
+  /** Last crash report by name */
+  crashReports: Record<string, CrashReport>;
+
+  // Actions
+  recordCrash: (report: CrashReport) => void;
+  clearFailed: (name: string) => void;
+}
+
+// =============================================================================
+// Store Creation
+// =============================================================================
+
+export const useAppHealthStore = create<AppHealthStoreState>()(
+  subscribeWithSelector((set) => ({
+    crashReports: {},
+
+    recordCrash: (report) =>
+      set((state) => ({ crashReports: { ...state.crashReports, [report.name]: report } })),
+
+    clearFailed: (name) =>
+      set((state) => {
+        const report = state.crashReports[name];
+        if (!report?.failed) return state;
+        return { crashReports: { ...state.crashReports, [name]: { ...report, failed: false } } };
+      }),
+  }))
+);
+
+// =============================================================================
+// Selectors
+// =============================================================================
+
+export const selectCrashReports = (state: AppHealthStoreState) => state.crashReports;
+
+export const selectCrashReport = (name: string) => (state: AppHealthStoreState) =>
+  state.crashReports[name];
+
+/** Whether `name` is in a crash loop */
+export const selectIsFailed = (name: string) => (state: AppHealthStoreState) =>
+  state.crashReports[name]?.failed ?? false;
@@ -4,6 +4,8 @@
  * Persists user preferences to localStorage:
  * - Active workspace index
  * - Per-workspace background selections
+ * - Focus policy for windows on other desktops
+ * - Saved desktop templates (project workspaces)
  *
  * Note: Theme and accent color are already persisted by @cypher-asi/zui's ThemeProvider
  * to localStorage key 'zui-theme'.
@@ -11,18 +13,32 @@
 
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
+import type { DesktopTemplate } from './types';
+
+/** What an app's focus request does for a window on another desktop */
+export type FocusPolicy = 'switch_desktop' | 'pull_window';
 
 interface DesktopPrefsState {
   /** Currently active workspace index */
   activeWorkspace: number;
   /** Per-workspace background selections (workspace index -> background id) */
   backgrounds: Record<number, string>;
+  /** Focus policy passed to the desktop engine on init */
+  focusPolicy: FocusPolicy;
+  /** Saved desktop templates by name */
+  templates: Record<string, DesktopTemplate>;
   /** Set the active workspace index */
   setActiveWorkspace: (index: number) => void;
   /** Set background for a specific workspace */
   setBackground: (workspaceIndex: number, backgroundId: string) => void;
   /** Get background for a specific workspace (defaults to 'grain') */
   getBackground: (workspaceIndex: number) => string;
+  /** Set the focus policy */
+  setFocusPolicy: (policy: FocusPolicy) => void;
+  /** Save a template, replacing any with the same name */
+  saveTemplate: (template: DesktopTemplate) => void;
+  /** Remove a saved template */
+  removeTemplate: (name: string) => void;
 }
 
 export const useDesktopPrefsStore = create<DesktopPrefsState>()(
@@ -30,12 +46,24 @@ export const useDesktopPrefsStore = create<DesktopPrefsState>()(
     (set, get) => ({
       activeWorkspace: 0,
       backgrounds: { 0: 'grain' },
+      focusPolicy: 'switch_desktop',
+      templates: {},
       setActiveWorkspace: (index) => set({ activeWorkspace: index }),
       setBackground: (workspaceIndex, backgroundId) =>
         set((state) => ({
           backgrounds: { ...state.backgrounds, [workspaceIndex]: backgroundId },
         })),
       getBackground: (workspaceIndex) => get().backgrounds[workspaceIndex] ?? 'grain',
+      setFocusPolicy: (focusPolicy) => set({ focusPolicy }),
+      saveTemplate: (template) =>
+        set((state) => ({
+          templates: { ...state.templates, [template.name]: template },
+        })),
+      removeTemplate: (name) =>
+        set((state) => {
+          const { [name]: _removed, ...templates } = state.templates;
+          return { templates };
+        }),
     }),
     { name: 'zero-desktop-prefs' }
   )
@@ -45,3 +73,6 @@ export const useDesktopPrefsStore = create<DesktopPrefsState>()(
 export const selectActiveWorkspace = (state: DesktopPrefsState): number => state.activeWorkspace;
 export const selectBackgrounds = (state: DesktopPrefsState): Record<number, string> =>
   state.backgrounds;
+export const selectFocusPolicy = (state: DesktopPrefsState): FocusPolicy => state.focusPolicy;
+export const selectTemplates = (state: DesktopPrefsState): Record<string, DesktopTemplate> =>
+  state.templates;
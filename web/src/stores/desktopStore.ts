@@ -22,6 +22,9 @@ interface DesktopStoreState {
   inVoid: boolean;
   viewport: ViewportState;
   showVoid: boolean;
+  fullscreenWindow: number | null;
+  carryTarget: number | null;
+  annotationRevision: number;
   workspaceInfo: WorkspaceInfo | null;
 
   // Actions
@@ -35,6 +38,9 @@ interface DesktopStoreState {
   syncFromFrame: (frame: {
     viewMode: ViewMode;
     showVoid: boolean;
+    fullscreenWindow: number | null;
+    carryTarget: number | null;
+    annotationRevision: number;
     viewport: ViewportState;
     workspaceInfo: WorkspaceInfo;
   }) => void;
@@ -52,6 +58,9 @@ export const useDesktopStore = create<DesktopStoreState>()(
     inVoid: false,
     viewport: { center: { x: 0, y: 0 }, zoom: 1 },
     showVoid: false,
+    fullscreenWindow: null,
+    carryTarget: null,
+    annotationRevision: 0,
     workspaceInfo: null,
 
     setDesktops: (desktops) => set({ desktops }),
@@ -66,6 +75,9 @@ export const useDesktopStore = create<DesktopStoreState>()(
         viewMode: frame.viewMode === 'workspace' ? 'desktop' : frame.viewMode,
         inVoid: frame.viewMode === 'void',
         showVoid: frame.showVoid,
+        fullscreenWindow: frame.fullscreenWindow,
+        carryTarget: frame.carryTarget,
+        annotationRevision: frame.annotationRevision,
         viewport: frame.viewport,
         activeIndex: frame.workspaceInfo.active,
         workspaceInfo: frame.workspaceInfo,
@@ -98,6 +110,15 @@ export const selectViewport = (state: DesktopStoreState) => state.viewport;
 /** Select whether void layer should be visible */
 export const selectShowVoid = (state: DesktopStoreState) => state.showVoid;
 
+/** Select the fullscreen window (null when none) */
+export const selectFullscreenWindow = (state: DesktopStoreState) => state.fullscreenWindow;
+
+/** Select the desktop tile a carried window would drop on (null when none) */
+export const selectCarryTarget = (state: DesktopStoreState) => state.carryTarget;
+
+/** Select the annotation revision (changes whenever strokes need redrawing) */
+export const selectAnnotationRevision = (state: DesktopStoreState) => state.annotationRevision;
+
 /** Select workspace info */
 export const selectWorkspaceInfo = (state: DesktopStoreState) => state.workspaceInfo;
 
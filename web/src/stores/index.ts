@@ -37,6 +37,9 @@ export {
   selectInVoid,
   selectViewport,
   selectShowVoid,
+  selectFullscreenWindow,
+  selectCarryTarget,
+  selectAnnotationRevision,
   selectWorkspaceInfo,
   selectDesktopCount,
   selectLayerOpacities,
@@ -87,10 +90,14 @@ export {
   selectTimeFormat24h,
   selectTimezone,
   selectRpcEndpoint,
+  selectNetworkOffline,
   selectSettingsIsLoading,
   selectSettingsIsSynced,
   selectSettingsError,
   selectPendingNavigation,
+  selectDisplaySettings,
+  selectLoginItems,
+  DEFAULT_DISPLAY_SETTINGS,
   formatTime,
   formatDate,
   formatShortDate,
@@ -118,11 +125,23 @@ export {
   type KeyScheme,
 } from './machineKeysStore';
 
+// App health store
+export {
+  useAppHealthStore,
+  selectCrashReports,
+  selectCrashReport,
+  selectIsFailed,
+  type CrashReport,
+} from './appHealthStore';
+
 // Desktop Preferences store (localStorage persistence)
 export {
   useDesktopPrefsStore,
   selectActiveWorkspace,
   selectBackgrounds,
+  selectFocusPolicy,
+  selectTemplates,
+  type FocusPolicy,
 } from './desktopPrefsStore';
 
 // Shared types
@@ -136,6 +155,16 @@ export type {
   DesktopInfo,
   ViewportState,
   WorkspaceInfo,
+  FolderBinding,
+  DesktopTemplate,
+  AnnotationTool,
+  AnnotationStroke,
+  WidgetKind,
+  Widget,
+  WidgetInfo,
+  ColorFilter,
+  DisplaySettings,
+  HotkeyCheatSheetEntry,
   FrameData,
   LayerOpacities,
 } from './types';
@@ -1,9 +1,11 @@
 /**
  * Settings Store - Centralized state for system settings.
  *
- * Manages time format, timezone, and other system preferences.
- * Syncs with TimeServiceClient for persistence via the time WASM process.
- * Falls back to localStorage when service is unavailable.
+ * Manages time format, timezone, accessibility display settings, and other
+ * system preferences. Syncs with TimeServiceClient for persistence via the
+ * time WASM process. Falls back to localStorage when service is unavailable.
+ * Offline mode lives in the network WASM process's policy and is never
+ * cached locally.
  */
 
 import { create } from 'zustand';
@@ -11,10 +13,13 @@ import { persist, subscribeWithSelector } from 'zustand/middleware';
 import {
   TimeServiceClient,
   DEFAULT_TIME_SETTINGS,
+  NetworkServiceClient,
   IdentityServiceClient,
   type Supervisor,
   type KeyScheme,
+  type LoginItem,
 } from '@/client-services';
+import type { DisplaySettings } from './types';
 
 // =============================================================================
 // Constants
@@ -23,12 +28,26 @@ import {
 /** Default RPC endpoint for Zero-ID service */
 export const DEFAULT_RPC_ENDPOINT = '127.0.0.1:9999';
 
+/** Default accessibility display settings (all off) */
+export const DEFAULT_DISPLAY_SETTINGS: DisplaySettings = {
+  high_contrast: false,
+  color_filter: 'none',
+  reduced_motion: false,
+};
+
 // =============================================================================
 // Store Types
 // =============================================================================
 
 /** Settings navigation areas */
-export type SettingsArea = 'general' | 'identity' | 'network' | 'permissions' | 'theme';
+export type SettingsArea =
+  | 'general'
+  | 'identity'
+  | 'network'
+  | 'clipboard'
+  | 'permissions'
+  | 'startup'
+  | 'theme';
 
 /** Settings sub-panel identifiers for deep-linking */
 export type SettingsSubPanel = 'neural-key' | 'machine-keys' | 'linked-accounts';
@@ -46,11 +65,18 @@ interface SettingsStoreState {
 
   // Network settings
   rpcEndpoint: string;
+  /** System-wide offline mode (network service policy) */
+  networkOffline: boolean;
+
+  // Accessibility display settings (applied by the desktop's display sync)
+  display: DisplaySettings;
 
   // Identity preferences
   defaultKeyScheme: KeyScheme;
   /** Default machine key ID for authentication (hex string) */
   defaultMachineId: string | null;
+  /** Apps launched after session start */
+  loginItems: LoginItem[];
   isLoadingPreferences: boolean;
 
   // Navigation state (replaces module-level pendingNavigation)
@@ -64,16 +90,20 @@ interface SettingsStoreState {
   // Internal: Service client references
   _serviceClient: TimeServiceClient | null;
   _identityClient: IdentityServiceClient | null;
+  _networkClient: NetworkServiceClient | null;
 
   // Actions
   setTimeFormat24h: (value: boolean) => Promise<void>;
   setTimezone: (value: string) => Promise<void>;
   setRpcEndpoint: (value: string) => void;
+  setNetworkOffline: (value: boolean) => Promise<void>;
+  setDisplaySettings: (value: Partial<DisplaySettings>) => void;
 
   // Identity preferences actions
   loadIdentityPreferences: (userId: bigint) => Promise<void>;
   setDefaultKeyScheme: (userId: bigint, scheme: KeyScheme) => Promise<void>;
   setDefaultMachineKey: (userId: bigint, machineId: string) => Promise<void>;
+  setLoginItems: (userId: bigint, items: LoginItem[]) => Promise<void>;
 
   // Navigation actions
   setPendingNavigation: (navigation: PendingNavigation) => void;
@@ -96,8 +126,11 @@ export const useSettingsStore = create<SettingsStoreState>()(
         timeFormat24h: DEFAULT_TIME_SETTINGS.time_format_24h,
         timezone: DEFAULT_TIME_SETTINGS.timezone,
         rpcEndpoint: DEFAULT_RPC_ENDPOINT,
+        networkOffline: false,
+        display: DEFAULT_DISPLAY_SETTINGS,
         defaultKeyScheme: 'classical',
         defaultMachineId: null,
+        loginItems: [],
         isLoadingPreferences: false,
 
         // Navigation state
@@ -109,6 +142,7 @@ export const useSettingsStore = create<SettingsStoreState>()(
 
         _serviceClient: null,
         _identityClient: null,
+        _networkClient: null,
 
         // Navigation actions
         setPendingNavigation: (navigation: PendingNavigation) => {
@@ -123,10 +157,21 @@ export const useSettingsStore = create<SettingsStoreState>()(
         initializeService: (supervisor: Supervisor) => {
           const timeClient = new TimeServiceClient(supervisor);
           const identityClient = new IdentityServiceClient(supervisor);
-          set({ _serviceClient: timeClient, _identityClient: identityClient });
+          const networkClient = new NetworkServiceClient(supervisor);
+          set({
+            _serviceClient: timeClient,
+            _identityClient: identityClient,
+            _networkClient: networkClient,
+          });
 
           // Sync from service on initialization
           get().syncFromService();
+          networkClient
+            .getPolicy()
+            .then((policy) => set({ networkOffline: policy.offline }))
+            .catch((error) => {
+              console.warn('[SettingsStore] Failed to load network policy:', error);
+            });
         },
 
         // Load identity preferences from VFS
@@ -143,6 +188,7 @@ export const useSettingsStore = create<SettingsStoreState>()(
             set({
               defaultKeyScheme: prefs.default_key_scheme,
               defaultMachineId: prefs.default_machine_id ?? null,
+              loginItems: prefs.login_items ?? [],
               isLoadingPreferences: false,
             });
             console.log('[SettingsStore] Loaded identity preferences:', prefs);
@@ -200,6 +246,29 @@ export const useSettingsStore = create<SettingsStoreState>()(
           }
         },
 
+        // Replace login items in VFS
+        setLoginItems: async (userId: bigint, items: LoginItem[]) => {
+          const prevItems = get().loginItems;
+          // Optimistic update - always set in store state for UI
+          set({ loginItems: items });
+
+          const client = get()._identityClient;
+          if (!client) {
+            console.log('[SettingsStore] No identity client available, login items set in memory only');
+            return;
+          }
+
+          try {
+            await client.setLoginItems(userId, items);
+            console.log('[SettingsStore] Updated login items:', items);
+          } catch (err) {
+            console.error('[SettingsStore] Failed to set login items:', err);
+            // Revert on error
+            set({ loginItems: prevItems });
+            throw err;
+          }
+        },
+
         // Sync settings from service
         syncFromService: async () => {
           const client = get()._serviceClient;
@@ -286,6 +355,35 @@ export const useSettingsStore = create<SettingsStoreState>()(
           set({ rpcEndpoint: value });
           console.log('[SettingsStore] RPC endpoint saved:', value);
         },
+
+        // Set offline mode (applies system-wide once the service persists it)
+        setNetworkOffline: async (value: boolean) => {
+          const prevValue = get().networkOffline;
+          const client = get()._networkClient;
+
+          // Optimistic update
+          set({ networkOffline: value, error: null });
+
+          if (client) {
+            try {
+              const policy = await client.setOffline(value);
+              set({ networkOffline: policy.offline });
+              console.log('[SettingsStore] Offline mode saved:', policy.offline);
+            } catch (error) {
+              console.warn('[SettingsStore] Failed to save offline mode:', error);
+              // Revert on error
+              set({
+                networkOffline: prevValue,
+                error: error instanceof Error ? error.message : 'Failed to save setting',
+              });
+            }
+          }
+        },
+
+        // Update accessibility display settings
+        setDisplaySettings: (value: Partial<DisplaySettings>) => {
+          set((state) => ({ display: { ...state.display, ...value } }));
+        },
       }),
       {
         name: 'zero-settings-store',
@@ -294,6 +392,7 @@ export const useSettingsStore = create<SettingsStoreState>()(
           timeFormat24h: state.timeFormat24h,
           timezone: state.timezone,
           rpcEndpoint: state.rpcEndpoint,
+          display: state.display,
         }),
       }
     )
@@ -313,6 +412,15 @@ export const selectTimezone = (state: SettingsStoreState) => state.timezone;
 /** Select RPC endpoint */
 export const selectRpcEndpoint = (state: SettingsStoreState) => state.rpcEndpoint;
 
+/** Select offline mode */
+export const selectNetworkOffline = (state: SettingsStoreState) => state.networkOffline;
+
+/** Select accessibility display settings */
+export const selectDisplaySettings = (state: SettingsStoreState) => state.display;
+
+/** Select login items */
+export const selectLoginItems = (state: SettingsStoreState) => state.loginItems;
+
 /** Select loading state */
 export const selectSettingsIsLoading = (state: SettingsStoreState) => state.isLoading;
 
@@ -108,6 +108,125 @@ export interface WorkspaceInfo {
   backgrounds: string[];
 }
 
+/** A named VFS folder bound to a desktop */
+export interface FolderBinding {
+  name: string;
+  path: string;
+}
+
+/**
+ * Saved desktop layout, matching Rust's DesktopTemplate (snake_case fields).
+ * Instantiated with create_desktop_from_template_json().
+ */
+export interface DesktopTemplate {
+  name: string;
+  background?: string | null;
+  camera?: { center: { x: number; y: number }; zoom: number } | null;
+  windows: Array<{
+    app_id: string;
+    title?: string;
+    position: { x: number; y: number };
+    size: { width: number; height: number };
+  }>;
+  folders?: FolderBinding[];
+}
+
+// =============================================================================
+// Minimap
+// =============================================================================
+
+/** Rect in normalized minimap coordinates (0-1 on both axes) */
+export interface MinimapRect {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+/** Overview of the active desktop, from Rust's minimap_layout() */
+export interface MinimapData {
+  /** Width / height of the area the minimap covers */
+  aspectRatio: number;
+  /** Windows back to front */
+  windows: Array<{ id: number; focused: boolean; rect: MinimapRect }>;
+  /** Visible part of the canvas (may extend past 0-1 when zoomed far out) */
+  viewport: MinimapRect;
+}
+
+// =============================================================================
+// Annotations
+// =============================================================================
+
+/** Annotation tool, matching Rust's AnnotationTool */
+export type AnnotationTool =
+  | { type: 'pen'; color: string; width: number }
+  | { type: 'eraser'; radius: number };
+
+/** A pen stroke in desktop-local canvas coordinates, from get_annotations_json() */
+export interface AnnotationStroke {
+  id: number;
+  color: string;
+  /** Width at full pressure, in canvas units */
+  width: number;
+  points: Array<{ position: { x: number; y: number }; pressure: number }>;
+}
+
+// =============================================================================
+// Widgets
+// =============================================================================
+
+/** Widget contents, matching Rust's WidgetKind */
+export type WidgetKind =
+  | { type: 'sticky_note'; text: string; color: string }
+  | { type: 'clock'; show_seconds: boolean }
+  | { type: 'resource_monitor' };
+
+/** A canvas widget in desktop-local coordinates, from get_widgets_json() */
+export interface Widget {
+  id: number;
+  kind: WidgetKind;
+  position: { x: number; y: number };
+  size: { width: number; height: number };
+}
+
+/** Widget screen position from tick_frame(), back to front */
+export interface WidgetInfo {
+  id: number;
+  kind: WidgetKind;
+  opacity: number;
+  screenRect: { x: number; y: number; width: number; height: number };
+}
+
+// =============================================================================
+// Accessibility
+// =============================================================================
+
+/** Color filter, matching Rust's ColorFilter */
+export type ColorFilter = 'none' | 'protanopia' | 'deuteranopia' | 'tritanopia' | 'grayscale';
+
+/** Accessibility display settings, matching Rust's DisplaySettings */
+export interface DisplaySettings {
+  high_contrast: boolean;
+  color_filter: ColorFilter;
+  reduced_motion: boolean;
+}
+
+// =============================================================================
+// Keyboard
+// =============================================================================
+
+/** One row of the hotkey cheat sheet (from get_hotkeys_json) */
+export interface HotkeyCheatSheetEntry {
+  category: 'Windows' | 'Desktops' | 'View' | 'Apps';
+  description: string;
+  /** Command name, e.g. 'focus' or 'next_desktop' */
+  command: string;
+  /** Set for directional commands (focus, move_window, resize_window) */
+  direction?: 'left' | 'right' | 'up' | 'down';
+  /** Every chord bound to the command, e.g. 'Ctrl+ArrowLeft' */
+  keys: string[];
+}
+
 // =============================================================================
 // Frame Data (from Rust's tick_frame())
 // =============================================================================
@@ -119,6 +238,8 @@ export interface WorkspaceInfo {
 export interface FrameData {
   viewport: ViewportState;
   windows: WindowInfo[];
+  /** Widgets on the shown desktop(s), drawn below windows */
+  widgets: WidgetInfo[];
   /** True during any activity (zoom/pan/drag) - for adaptive framerate */
   animating: boolean;
   /** True only during layer transitions (void enter/exit) - for crossfade */
@@ -127,6 +248,14 @@ export interface FrameData {
   showVoid: boolean;
   /** Current view mode */
   viewMode: ViewMode;
+  /** Fullscreen window on the shown desktop (taskbar is hidden while set) */
+  fullscreenWindow: number | null;
+  /** Minimap layout, only present while the minimap is shown */
+  minimap: MinimapData | null;
+  /** Desktop tile under a window being carried in the void, for highlighting */
+  carryTarget: number | null;
+  /** Bumped on every annotation change; refetch strokes when it (or the active desktop) changes */
+  annotationRevision: number;
   workspaceInfo: WorkspaceInfo;
   workspaceDimensions: {
     width: number;
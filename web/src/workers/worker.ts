@@ -180,7 +180,7 @@ self.onmessage = async (event: MessageEvent) => {
     if (importsMemory) {
       sharedMemory = new WebAssembly.Memory({
         initial: 32, // 2MB initial (32 * 64KB)
-        maximum: 64, // 4MB max (64 * 64KB)
+        maximum: 256, // 16MB max (256 * 64KB), matches --max-memory
         shared: true,
       });
       (importObject.env as Record<string, unknown>).memory = sharedMemory;
@@ -55,12 +55,13 @@ export function createMockDesktopController(
   _updateState: (updates: Partial<MockDesktopControllerState>) => void;
 } {
   const state: MockDesktopControllerState = { ...defaultState, ...initialState };
+  const folders: Record<number, Array<{ name: string; path: string }>> = {};
 
   const updateState = (updates: Partial<MockDesktopControllerState>) => {
     Object.assign(state, updates);
   };
 
-  return {
+  const controller: ReturnType<typeof createMockDesktopController> = {
     _state: state,
     _updateState: updateState,
 
@@ -154,16 +155,88 @@ export function createMockDesktopController(
         window.state = 'normal';
       }
     }),
+    toggle_fullscreen: vi.fn((id: bigint) => {
+      const window = state.windows.find((w) => w.id === Number(id));
+      if (window) {
+        window.state = window.state === 'fullscreen' ? 'normal' : 'fullscreen';
+      }
+    }),
+    exit_fullscreen: vi.fn((id: bigint) => {
+      const window = state.windows.find((w) => w.id === Number(id));
+      if (window && window.state === 'fullscreen') {
+        window.state = 'normal';
+      }
+    }),
+    get_fullscreen_window: vi.fn(() => {
+      const window = state.windows.find((w) => w.state === 'fullscreen');
+      return window ? BigInt(window.id) : undefined;
+    }),
     get_focused_window: vi.fn(() =>
       state.focusedWindow !== null ? BigInt(state.focusedWindow) : undefined
     ),
+    request_focus: vi.fn((id: bigint) => {
+      const window = state.windows.find((w) => w.id === Number(id));
+      if (!window) return;
+      if (window.state === 'minimized') window.state = 'normal';
+      state.focusedWindow = window.id;
+      state.windows.forEach((w) => (w.focused = w.id === window.id));
+    }),
+    set_focus_policy: vi.fn(
+      (policy: string) => policy === 'switch_desktop' || policy === 'pull_window'
+    ),
+    get_focus_policy: vi.fn(() => 'switch_desktop'),
     pan_to_window: vi.fn((id: bigint) => {
       const window = state.windows.find((w) => w.id === Number(id));
       if (window) {
         state.viewport.center = { ...window.position };
       }
     }),
+    zoom_to_window: vi.fn((id: bigint) => {
+      const window = state.windows.find((w) => w.id === Number(id));
+      if (window) {
+        state.viewport.center = { ...window.position };
+      }
+    }),
+    zoom_to_fit_all: vi.fn(() => {
+      // Camera fit is computed in Rust
+    }),
+    zoom_to_rect: vi.fn((x: number, y: number, width: number, height: number) => {
+      state.viewport.center = { x: x + width / 2, y: y + height / 2 };
+    }),
     get_windows_json: vi.fn(() => JSON.stringify(state.windows)),
+
+    // Annotations
+    set_annotation_tool_json: vi.fn((_json: string) => true),
+    begin_stroke: vi.fn((_x: number, _y: number, _pressure: number) => false),
+    extend_stroke: vi.fn((_x: number, _y: number, _pressure: number) => {
+      // no-op in mock
+    }),
+    end_stroke: vi.fn(() => undefined),
+    undo_stroke: vi.fn(() => false),
+    clear_annotations: vi.fn((_index: number) => true),
+    get_annotations_json: vi.fn(() => '[]'),
+
+    // Widgets
+    add_widget_json: vi.fn((_index: number, _kind: string, _x?: number, _y?: number) => -1),
+    update_widget_json: vi.fn((_id: bigint, _kind: string) => false),
+    move_widget: vi.fn((_id: bigint, _x: number, _y: number) => false),
+    resize_widget: vi.fn((_id: bigint, _w: number, _h: number) => false),
+    remove_widget: vi.fn((_id: bigint) => false),
+    get_widgets_json: vi.fn((_index: number) => '[]'),
+    widget_at: vi.fn((_x: number, _y: number) => undefined),
+    start_widget_drag: vi.fn((_id: bigint, _x: number, _y: number) => {
+      // no-op in mock
+    }),
+
+    // Minimap
+    set_minimap_visible: vi.fn((_visible: boolean) => {
+      // Layout is computed in Rust
+    }),
+    get_minimap_json: vi.fn(() => 'null'),
+    minimap_navigate: vi.fn((_x: number, _y: number) => {
+      // Camera is moved in Rust
+    }),
+
     get_window_screen_rects_json: vi.fn(() =>
       JSON.stringify(
         state.windows.map((w, i) => ({
@@ -222,6 +295,81 @@ export function createMockDesktopController(
         gap: 100,
       })
     ),
+    move_window_to_desktop: vi.fn((_window_id: bigint, desktop_index: number) => {
+      return desktop_index >= 0 && desktop_index < state.desktops.length;
+    }),
+    rename_desktop: vi.fn((index: number, name: string) => {
+      const desktop = state.desktops[index];
+      if (!desktop) return false;
+      desktop.name = name;
+      return true;
+    }),
+    move_desktop: vi.fn((from: number, to: number) => {
+      if (from >= state.desktops.length || to >= state.desktops.length) return false;
+      const [desktop] = state.desktops.splice(from, 1);
+      state.desktops.splice(to, 0, desktop);
+      state.activeDesktop = state.desktops.findIndex((d) => d.active);
+      return true;
+    }),
+    delete_desktop: vi.fn((index: number, rehome_to: number) => {
+      if (state.desktops.length <= 1 || index === rehome_to) {
+        return 'Invalid operation: delete_desktop';
+      }
+      const removed = state.desktops[index];
+      const target = state.desktops[rehome_to];
+      if (!removed || !target) return 'Desktop index out of bounds';
+      target.windowCount += removed.windowCount;
+      state.desktops.splice(index, 1);
+      if (!state.desktops.some((d) => d.active)) {
+        state.desktops[Math.max(0, index - 1)].active = true;
+      }
+      state.activeDesktop = state.desktops.findIndex((d) => d.active);
+      return '';
+    }),
+    duplicate_desktop: vi.fn((index: number) => {
+      const source = state.desktops[index];
+      if (!source) return -1;
+      const id = Math.max(...state.desktops.map((d) => d.id)) + 1;
+      const copy = { ...source, id, name: `${source.name} copy`, active: false };
+      state.desktops.splice(index + 1, 0, copy);
+      state.activeDesktop = state.desktops.findIndex((d) => d.active);
+      return index + 1;
+    }),
+    get_desktop_template_json: vi.fn((index: number) => {
+      const desktop = state.desktops[index];
+      return desktop ? JSON.stringify({ name: desktop.name, windows: [] }) : 'null';
+    }),
+    create_desktop_from_template_json: vi.fn((json: string) => {
+      try {
+        const template = JSON.parse(json) as {
+          name: string;
+          windows?: Array<{ app_id: string }>;
+        };
+        const id = Math.max(...state.desktops.map((d) => d.id)) + 1;
+        const windows = (template.windows ?? []).map((w, i) => ({
+          id: state.windows.length + 1 + i,
+          appId: w.app_id,
+        }));
+        state.desktops.push({ id, name: template.name, active: false, windowCount: windows.length });
+        return JSON.stringify({ desktop: state.desktops.length - 1, windows });
+      } catch {
+        return 'null';
+      }
+    }),
+    set_desktop_folders_json: vi.fn((index: number, json: string) => {
+      if (!state.desktops[index]) return 'desktop index out of bounds';
+      folders[index] = JSON.parse(json) as Array<{ name: string; path: string }>;
+      return '';
+    }),
+    get_desktop_folders_json: vi.fn((index: number) => JSON.stringify(folders[index] ?? [])),
+    export_snapshot_json: vi.fn(() =>
+      JSON.stringify({
+        version: 1,
+        active_desktop: state.activeDesktop,
+        desktops: state.desktops.map((d) => ({ id: d.id, name: d.name })),
+      })
+    ),
+    import_snapshot_json: vi.fn((_json: string) => ''),
 
     // Void mode
     get_view_mode: vi.fn(() => state.viewMode),
@@ -243,6 +391,67 @@ export function createMockDesktopController(
     is_transitioning: vi.fn(() => state.isTransitioning),
     tick_transition: vi.fn(() => state.isTransitioning),
 
+    // Accessibility
+    get_display_settings_json: vi.fn(() =>
+      JSON.stringify({
+        high_contrast: false,
+        color_filter: 'none',
+        reduced_motion: false,
+        color_matrix: [
+          [1, 0, 0],
+          [0, 1, 0],
+          [0, 0, 1],
+        ],
+      })
+    ),
+    set_display_settings_json: vi.fn((_json: string) => true),
+
+    // Keyboard (emulates the default bindings the tests use)
+    handle_key: vi.fn(
+      (key: string, ctrl: boolean, shift: boolean, alt: boolean, meta: boolean) => {
+        const plain = !ctrl && !shift && !alt && !meta;
+        if ((ctrl && key === '`') || (plain && key === 'F3')) {
+          if (state.viewMode === 'void') {
+            controller.exit_void(state.activeDesktop);
+          } else {
+            controller.enter_void();
+          }
+          return JSON.stringify({ type: 'handled' });
+        }
+        if (ctrl && !shift && !alt && (key === 'ArrowLeft' || key === 'ArrowRight')) {
+          const count = state.desktops.length;
+          if (count <= 1) return JSON.stringify({ type: 'unhandled' });
+          const step = key === 'ArrowLeft' ? count - 1 : 1;
+          const next = (state.activeDesktop + step) % count;
+          if (state.viewMode === 'void') {
+            controller.exit_void(next);
+          } else {
+            controller.switch_desktop(next);
+          }
+          return JSON.stringify({ type: 'handled' });
+        }
+        if (plain && key.toLowerCase() === 't') {
+          return JSON.stringify({ type: 'frontend', command: 'launch_terminal' });
+        }
+        if (plain && key.toLowerCase() === 'c') {
+          return JSON.stringify({ type: 'frontend', command: 'close_window' });
+        }
+        return JSON.stringify({ type: 'unhandled' });
+      }
+    ),
+    run_key_command_json: vi.fn((_json: string) => JSON.stringify({ type: 'unhandled' })),
+    get_hotkeys_json: vi.fn(() => '[]'),
+    bind_hotkey: vi.fn((_chord: string, _command_json: string) => ''),
+    unbind_hotkey: vi.fn((_chord: string) => false),
+
+    // Input recording
+    start_input_recording: vi.fn(),
+    stop_input_recording: vi.fn(() => 'null'),
+    is_recording_input: vi.fn(() => false),
+    replay_input_recording: vi.fn((_json: string) =>
+      JSON.stringify({ outputs: [], failure: null, now_ms: 0 })
+    ),
+
     // Input handling
     pointer_down: vi.fn(
       (_x: number, _y: number, _button: number, _ctrl: boolean, _shift: boolean) =>
@@ -257,6 +466,8 @@ export function createMockDesktopController(
       (_window_id: bigint, _direction: string, _x: number, _y: number) => {}
     ),
     start_window_drag: vi.fn((_window_id: bigint, _x: number, _y: number) => {}),
+    start_window_carry: vi.fn((_window_id: bigint, _x: number, _y: number) => {}),
+    get_desktop_tile_at: vi.fn((_x: number, _y: number) => -1),
 
     // Unified frame tick
     tick_frame: vi.fn(() =>
@@ -278,10 +489,15 @@ export function createMockDesktopController(
             height: w.size.height,
           },
         })),
+        widgets: [],
         animating: state.isAnimating,
         transitioning: state.isTransitioning,
         showVoid: state.viewMode === 'void',
         viewMode: state.viewMode,
+        fullscreenWindow: null,
+        minimap: null,
+        carryTarget: null,
+        annotationRevision: 0,
         workspaceInfo: {
           count: state.desktops.length,
           active: state.activeDesktop,
@@ -296,6 +512,7 @@ export function createMockDesktopController(
       })
     ),
   };
+  return controller;
 }
 
 // Helper to create a controller with pre-configured windows
@@ -59,7 +59,9 @@ export function createMockSupervisor(
     send_input: vi.fn((_input: string) => {}),
     register_console_callback: vi.fn((_pid: bigint, _callback: (text: string) => void) => {}),
     unregister_console_callback: vi.fn((_pid: bigint) => {}),
-    set_spawn_callback: vi.fn((_callback: (procType: string, name: string) => void) => {}),
+    set_spawn_callback: vi.fn(
+      (_callback: (procType: string, name: string, requestId: bigint) => void) => {}
+    ),
     complete_spawn: vi.fn((name: string, binary: Uint8Array) => {
       const pid = state.processes.length + 1;
       state.processes.push({
@@ -70,6 +72,7 @@ export function createMockSupervisor(
       });
       return BigInt(pid);
     }),
+    complete_requested_spawn: vi.fn((_requestId: bigint, _binary: Uint8Array) => BigInt(0)),
     init_axiom_storage: vi.fn(async () => true),
     sync_axiom_log: vi.fn(async () => 0),
     poll_syscalls: vi.fn(() => 0),
@@ -102,6 +105,7 @@ export function createMockSupervisor(
       )
     ),
     get_endpoint_list_json: vi.fn(() => JSON.stringify([])),
+    get_service_graph_json: vi.fn(() => JSON.stringify({ nodes: [], edges: [] })),
     get_ipc_traffic_json: vi.fn((_count: number) => JSON.stringify([])),
     get_system_metrics_json: vi.fn(() =>
       JSON.stringify({
@@ -121,6 +125,9 @@ export function createMockSupervisor(
     ),
     get_commitlog_json: vi.fn((_count: number) => JSON.stringify([])),
     get_syslog_json: vi.fn((_count: number) => JSON.stringify([])),
+    get_async_ops_json: vi.fn(() => JSON.stringify([])),
+    get_network_stats_json: vi.fn(() => JSON.stringify({ offline: false, apps: [] })),
+    fail_stuck_async_ops: vi.fn((_minAgeMs: number) => 0),
 
     // Process isolation APIs
     send_input_to_process: vi.fn((_pid: number, _input: string) => {}),
@@ -135,6 +142,34 @@ export function createMockSupervisor(
       const responseTag = tag + 1;
       return responseTag.toString(16).padStart(8, '0');
     }),
+
+    // Speech output
+    set_speech_callback: vi.fn((_callback: (action: string, json: string) => void) => {}),
+    speech_finished: vi.fn((_utteranceId: number) => {}),
+    announce: vi.fn((_text: string) => false),
+
+    // Printing
+    set_print_callback: vi.fn((_callback: (json: string) => void) => {}),
+    print_finished: vi.fn((_jobId: number, _outcome: string, _error?: string) => {}),
+
+    // Thumbnails
+    set_thumbnail_callback: vi.fn(
+      (_callback: (id: number, size: number, image: Uint8Array) => void) => {}
+    ),
+    thumbnail_max_bytes: vi.fn(() => 16361),
+    thumbnail_finished: vi.fn(
+      (_id: number, _width: number, _height: number, _data: Uint8Array) => {}
+    ),
+    thumbnail_failed: vi.fn((_id: number, _error: string) => {}),
+
+    // Notifications
+    set_notification_callback: vi.fn((_callback: (json: string) => void) => {}),
+
+    // Crash reports
+    set_crash_callback: vi.fn((_callback: (json: string) => void) => {}),
+    get_crash_reports_json: vi.fn(() => '[]'),
+    reset_crashes: vi.fn((_name: string) => true),
+    delete_app_data: vi.fn((_userId: string, _appId: string) => true),
   };
 }
 